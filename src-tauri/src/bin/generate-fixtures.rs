@@ -0,0 +1,98 @@
+#![allow(clippy::disallowed_macros)] // Binaries can use println!
+
+//! Write named test fixture sets to disk
+//!
+//! ```text
+//! cargo run --features test-fixtures --bin generate-fixtures -- \
+//!     --out /tmp/fixtures [--include-heavy] [SET...]
+//! ```
+//!
+//! With no set names, every non-heavy set is written. Each set goes to
+//! `<out>/<name>` and its digest is printed so trees can be compared across
+//! machines.
+
+#[allow(dead_code)]
+#[path = "../../tests/common/fixtures/builder.rs"]
+mod builder;
+
+#[allow(dead_code)]
+#[path = "../../tests/common/fixtures/sets.rs"]
+mod sets;
+
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+fn main() -> ExitCode {
+    let mut out = None;
+    let mut include_heavy = false;
+    let mut names = Vec::new();
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--out" => out = args.next().map(PathBuf::from),
+            "--include-heavy" => include_heavy = true,
+            "--list" => {
+                for set in sets::FIXTURE_SETS {
+                    let heavy = if set.heavy { " (heavy)" } else { "" };
+                    println!("{}{} - {}", set.name, heavy, set.description);
+                }
+                return ExitCode::SUCCESS;
+            }
+            name => names.push(name.to_string()),
+        }
+    }
+
+    let Some(out) = out else {
+        eprintln!("Usage: generate-fixtures --out DIR [--include-heavy] [--list] [SET...]");
+        return ExitCode::FAILURE;
+    };
+
+    let selected: Vec<&sets::NamedFixtureSet> = if names.is_empty() {
+        sets::FIXTURE_SETS
+            .iter()
+            .filter(|set| include_heavy || !set.heavy)
+            .collect()
+    } else {
+        let mut selected = Vec::new();
+        for name in &names {
+            match sets::fixture_set(name) {
+                Some(set) => selected.push(set),
+                None => {
+                    eprintln!("Unknown fixture set '{name}' (see --list)");
+                    return ExitCode::FAILURE;
+                }
+            }
+        }
+        selected
+    };
+
+    for set in selected {
+        let root = out.join(set.name);
+        if root.exists() {
+            eprintln!("Skipping {}: {} already exists", set.name, root.display());
+            continue;
+        }
+
+        let tree = match (set.build)().build(&root) {
+            Ok(tree) => tree,
+            Err(e) => {
+                eprintln!("Failed to build {}: {e}", set.name);
+                return ExitCode::FAILURE;
+            }
+        };
+        let digest = tree
+            .digest()
+            .unwrap_or_else(|e| format!("unavailable ({e})"));
+        println!(
+            "{}: {} entries, {} bytes, sha256 {} -> {}",
+            set.name,
+            tree.entries.len(),
+            tree.total_size(),
+            digest,
+            root.display()
+        );
+    }
+
+    ExitCode::SUCCESS
+}
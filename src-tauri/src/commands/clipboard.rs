@@ -0,0 +1,308 @@
+//! Clipboard commands
+//!
+//! Copies recipients, fingerprints, recovery codes and archive paths to the
+//! clipboard by reference: the interface names the key, contact, share or
+//! archive, and the value is resolved here rather than sent back for the
+//! copy. Recovery codes still reach the interface once, when their shares
+//! are created and shown. Each copy is cleared after the configured time if
+//! the clipboard still holds it, and recorded, redacted, in the operation
+//! log. A panic lock clears it at once.
+//!
+//! Copying is off until the user turns it on. Copying a recovery code also
+//! needs the typed confirmation.
+
+use crate::commands::types::{
+    CommandError, CommandResponse, ErrorCode, ValidateInput, ValidationRule,
+};
+use crate::commands::validation::{ExistingVaultId, NonEmptyId, input_rules, invalid};
+use crate::prelude::*;
+use crate::services::key_management::passphrase::{PassphraseManager, RecoveryShareError};
+use crate::services::key_management::shared::KeyManager;
+use crate::services::key_management::shared::domain::models::contact::recipient_fingerprint;
+use crate::services::key_management::yubikey::infrastructure::pty::app_handle::get_app_handle;
+use crate::services::vault::VaultManager;
+use crate::services::vault::application::services::{ClipboardBackend, ClipboardCopy};
+use crate::services::vault::domain::VaultError;
+use crate::services::vault::domain::models::{
+    ClipboardGuardStatus, ClipboardKind, ClipboardSettings, MAX_CLIPBOARD_CLEAR_AFTER,
+    MIN_CLIPBOARD_CLEAR_AFTER,
+};
+use crate::services::vault::infrastructure::persistence::{APP_LOG_SCOPE, AppConfig};
+use std::sync::Arc;
+use tauri::AppHandle;
+use tauri_plugin_clipboard_manager::ClipboardExt;
+use zeroize::Zeroizing;
+
+/// The system clipboard, through the clipboard plugin
+pub(crate) struct TauriClipboard(Arc<AppHandle>);
+
+impl TauriClipboard {
+    pub(crate) fn get() -> Result<Self, Box<CommandError>> {
+        get_app_handle().map(Self).ok_or_else(|| {
+            Box::new(CommandError::operation(
+                ErrorCode::InternalError,
+                "The clipboard isn't available yet",
+            ))
+        })
+    }
+}
+
+impl ClipboardBackend for TauriClipboard {
+    fn write_text(&self, text: &str) -> Result<(), String> {
+        self.0
+            .clipboard()
+            .write_text(text.to_string())
+            .map_err(|e| e.to_string())
+    }
+
+    fn read_text(&self) -> Result<Option<String>, String> {
+        // An empty clipboard, or one holding an image, reads as an error
+        Ok(self.0.clipboard().read_text().ok())
+    }
+
+    fn clear(&self) -> Result<(), String> {
+        self.0.clipboard().clear().map_err(|e| e.to_string())
+    }
+}
+
+/// A value to copy, by reference
+#[derive(Debug, Deserialize, specta::Type)]
+pub struct CopyToClipboardInput {
+    pub kind: ClipboardKind,
+    /// Key or contact ID for recipients and fingerprints, share number
+    /// (from 1) for recovery codes, archive ID for archive paths
+    pub value_ref: String,
+    /// Vault the recovery code or archive belongs to
+    #[serde(default)]
+    pub vault_id: Option<String>,
+    /// For recovery codes, the user must type "COPY"
+    #[serde(default)]
+    pub confirmation: Option<String>,
+}
+
+fn require_vault_for_kind(input: &CopyToClipboardInput) -> Result<(), Box<CommandError>> {
+    let needs_vault = matches!(
+        input.kind,
+        ClipboardKind::RecoveryCode | ClipboardKind::ArchivePath
+    );
+    if needs_vault && input.vault_id.is_none() {
+        return Err(Box::new(invalid(
+            "vault_id",
+            ValidationRule::ExistingVaultId,
+            format!("A vault is required to copy a {}", input.kind.as_str()),
+        )));
+    }
+    Ok(())
+}
+
+input_rules! {
+    CopyToClipboardInput {
+        value_ref("Value reference"): [NonEmptyId],
+        vault_id("Vault ID"): [ExistingVaultId],
+    }
+    then require_vault_for_kind
+}
+
+/// Whether values may be copied, and for how long they stay
+#[derive(Debug, Deserialize, specta::Type)]
+pub struct SetClipboardSettingsInput {
+    pub settings: ClipboardSettings,
+}
+
+fn check_clear_after(input: &SetClipboardSettingsInput) -> Result<(), Box<CommandError>> {
+    let clear_after = input.settings.clear_after;
+    if !(MIN_CLIPBOARD_CLEAR_AFTER..=MAX_CLIPBOARD_CLEAR_AFTER).contains(&clear_after) {
+        return Err(Box::new(invalid(
+            "clear_after",
+            ValidationRule::InRange,
+            format!(
+                "Clear after must be between {} and {}",
+                MIN_CLIPBOARD_CLEAR_AFTER, MAX_CLIPBOARD_CLEAR_AFTER
+            ),
+        )));
+    }
+    Ok(())
+}
+
+input_rules! {
+    SetClipboardSettingsInput {}
+    then check_clear_after
+}
+
+fn clipboard_error(e: VaultError) -> Box<CommandError> {
+    match e {
+        VaultError::InvalidOperation(message) => Box::new(
+            CommandError::operation(ErrorCode::InvalidInput, message).with_recovery_guidance(
+                "Turn on clipboard copying, and type COPY exactly to copy a recovery code",
+            ),
+        ),
+        VaultError::NotFound(what) => Box::new(CommandError::operation(
+            ErrorCode::FileNotFound,
+            format!("No {} in this vault", what),
+        )),
+        e => Box::new(
+            CommandError::operation(ErrorCode::StorageFailed, "Failed to copy to the clipboard")
+                .with_details(e.to_string()),
+        ),
+    }
+}
+
+fn key_not_found(value_ref: &str) -> Box<CommandError> {
+    Box::new(CommandError::operation(
+        ErrorCode::KeyNotFound,
+        format!("No key or contact with ID '{}'", value_ref),
+    ))
+}
+
+/// Recipient of the key or contact `value_ref`
+fn resolve_recipient(value_ref: &str) -> Result<String, Box<CommandError>> {
+    let registry = KeyManager::new().load_registry().map_err(|e| {
+        Box::new(
+            CommandError::operation(ErrorCode::StorageFailed, "Failed to load key registry")
+                .with_details(e.to_string()),
+        )
+    })?;
+    let recipient = match registry.get_key(value_ref) {
+        Some(entry) => entry.public_key().to_string(),
+        None => registry
+            .contacts
+            .get(value_ref)
+            .map(|contact| contact.recipient.clone())
+            .ok_or_else(|| key_not_found(value_ref))?,
+    };
+    if recipient.is_empty() {
+        return Err(Box::new(
+            CommandError::operation(
+                ErrorCode::InvalidKeyState,
+                "This key has no recipient until its YubiKey is registered",
+            )
+            .with_recovery_guidance("Finish setting up the YubiKey first"),
+        ));
+    }
+    Ok(recipient)
+}
+
+/// Held recovery share `value_ref` of `vault_id`
+fn resolve_recovery_code(
+    vault_id: &str,
+    value_ref: &str,
+) -> Result<Zeroizing<String>, Box<CommandError>> {
+    let number: u8 = value_ref.trim().parse().map_err(|_| {
+        Box::new(invalid(
+            "value_ref",
+            ValidationRule::InRange,
+            "Share number must be a number from 1",
+        ))
+    })?;
+    PassphraseManager::new()
+        .recovery_share(vault_id, number)
+        .map_err(|e| match e {
+            RecoveryShareError::ShareNotHeld(_) => Box::new(
+                CommandError::operation(ErrorCode::InvalidInput, e.to_string())
+                    .with_recovery_guidance("Copy each share while the new shares are shown"),
+            ),
+            e => Box::new(invalid("value_ref", ValidationRule::InRange, e.to_string())),
+        })
+}
+
+/// Put a value on the clipboard, to be cleared after the configured time
+///
+/// Recovery codes can only be copied for a few minutes after their shares
+/// are created, and only with the confirmation. The clear is skipped if
+/// something else has been copied since.
+#[tauri::command]
+#[specta::specta]
+#[instrument(skip(input), fields(kind = input.kind.as_str()))]
+pub async fn copy_to_clipboard(
+    input: CopyToClipboardInput,
+) -> CommandResponse<ClipboardGuardStatus> {
+    input.validate()?;
+
+    let manager = VaultManager::new();
+    let settings = manager
+        .check_clipboard_copy(input.kind, input.confirmation.as_deref())
+        .map_err(clipboard_error)?;
+
+    let vault_id = input.vault_id.as_deref().unwrap_or(APP_LOG_SCOPE);
+    let value = match input.kind {
+        ClipboardKind::Recipient => Zeroizing::new(resolve_recipient(&input.value_ref)?),
+        ClipboardKind::Fingerprint => {
+            Zeroizing::new(recipient_fingerprint(&resolve_recipient(&input.value_ref)?))
+        }
+        ClipboardKind::RecoveryCode => resolve_recovery_code(vault_id, &input.value_ref)?,
+        ClipboardKind::ArchivePath => Zeroizing::new(
+            manager
+                .get_archive_path(vault_id, &input.value_ref)
+                .map_err(clipboard_error)?
+                .display()
+                .to_string(),
+        ),
+    };
+
+    let clipboard = TauriClipboard::get()?;
+    let generation = manager
+        .copy_to_clipboard(
+            &clipboard,
+            ClipboardCopy {
+                kind: input.kind,
+                value_ref: &input.value_ref,
+                log_scope: vault_id,
+                value: &value,
+            },
+            settings.clear_after,
+        )
+        .map_err(clipboard_error)?;
+    drop(value);
+
+    let clear_after = settings.clear_after;
+    let spawned = std::thread::Builder::new()
+        .name("clipboard-clear".to_string())
+        .spawn(move || {
+            std::thread::sleep(clear_after.into());
+            if let Err(e) = VaultManager::new().clear_clipboard_if_unchanged(&clipboard, generation)
+            {
+                warn!(error = %e, "Failed to clear the clipboard");
+            }
+        });
+    if let Err(e) = spawned {
+        warn!(error = %e, "Failed to schedule clearing the clipboard");
+    }
+
+    manager
+        .get_clipboard_guard_status()
+        .map_err(clipboard_error)
+}
+
+/// Clipboard settings and the copy waiting to be cleared, for a countdown
+#[tauri::command]
+#[specta::specta]
+#[instrument]
+pub async fn get_clipboard_guard_status() -> CommandResponse<ClipboardGuardStatus> {
+    VaultManager::new()
+        .get_clipboard_guard_status()
+        .map_err(clipboard_error)
+}
+
+/// Turn clipboard copying on or off and set how long copies stay
+///
+/// A copy already on the clipboard keeps the time it was copied with.
+#[tauri::command]
+#[specta::specta]
+#[instrument(skip_all, fields(enabled = input.settings.enabled))]
+pub async fn set_clipboard_settings(input: SetClipboardSettingsInput) -> CommandResponse<()> {
+    input.validate()?;
+
+    let save = AppConfig::load().and_then(|mut config| {
+        config.clipboard = input.settings;
+        config.save()
+    });
+    save.map_err(|e| {
+        Box::new(
+            CommandError::operation(
+                ErrorCode::StorageFailed,
+                format!("Failed to save clipboard settings: {}", e),
+            )
+            .with_recovery_guidance("Check that the config directory is writable"),
+        )
+    })
+}
@@ -0,0 +1,163 @@
+//! Archive parameter migration commands
+//!
+//! Thin wrapper following Command → Manager → Service pattern.
+//! Plans which archives don't match new encryption parameters, and
+//! re-encrypts an archive under them after checking every file.
+
+use crate::commands::reclaim_storage;
+use crate::commands::types::{CommandError, CommandResponse, ErrorCode, ValidateInput};
+use crate::commands::validation::{ExistingVaultId, NonEmpty, NonEmptyId, input_rules};
+use crate::features::{FeatureFlag, require_feature};
+use crate::prelude::*;
+use crate::services::crypto::domain::models::{
+    ArchiveCompression, ArchiveMigrationReport, EncryptionParameters, ParameterMigrationPlan,
+};
+use crate::services::crypto::{CryptoError, CryptoManager};
+use crate::services::shared::infrastructure::progress::{OperationKind, begin_operation};
+use age::secrecy::SecretString;
+
+/// Input for planning a parameter migration
+#[derive(Debug, Deserialize, specta::Type)]
+pub struct PlanParameterMigrationInput {
+    pub vault_id: String,
+    pub target_parameters: EncryptionParameters,
+}
+
+input_rules! {
+    PlanParameterMigrationInput {
+        vault_id("Vault ID"): [ExistingVaultId],
+    }
+}
+
+/// Input for migrating one archive
+#[derive(Debug, Deserialize, specta::Type)]
+pub struct MigrateArchiveInput {
+    pub vault_id: String,
+    /// Archive to migrate (see `plan_parameter_migration`); must be current
+    pub archive_id: String,
+    pub target_parameters: EncryptionParameters,
+    pub key_id: String,
+    /// Key passphrase, or the PIN for a YubiKey
+    pub passphrase: String,
+    /// Mark the original archive eligible for pruning
+    #[serde(default)]
+    pub prune_original: bool,
+}
+
+input_rules! {
+    MigrateArchiveInput {
+        vault_id("Vault ID"): [ExistingVaultId],
+        archive_id("Archive ID"): [NonEmptyId],
+        key_id("Key ID"): [NonEmptyId],
+        passphrase("Passphrase"): [NonEmpty],
+    }
+}
+
+fn check_target(target: &EncryptionParameters) -> CommandResponse<()> {
+    if target.compression == ArchiveCompression::Zstd {
+        require_feature(FeatureFlag::ZstdCompression)?;
+    }
+    target.validate().map_err(|e| {
+        Box::new(
+            CommandError::validation(e)
+                .with_recovery_guidance("Choose gzip compression at level 0-9"),
+        )
+    })
+}
+
+/// List a vault's archives that don't match the target parameters, with
+/// the time and space migrating them would take; nothing is changed
+#[tauri::command]
+#[specta::specta]
+#[instrument(skip_all, fields(vault_id = %input.vault_id))]
+pub async fn plan_parameter_migration(
+    input: PlanParameterMigrationInput,
+) -> CommandResponse<ParameterMigrationPlan> {
+    input.validate()?;
+    check_target(&input.target_parameters)?;
+
+    CryptoManager::new()
+        .plan_parameter_migration(&input.vault_id, &input.target_parameters)
+        .map_err(|e| {
+            Box::new(
+                CommandError::operation(ErrorCode::InvalidInput, e.to_string())
+                    .with_recovery_guidance("Check the vault exists and its archives are listed"),
+            )
+        })
+}
+
+/// Re-encrypt a vault's current archive under the target parameters
+///
+/// Every file is checked against the archive's manifest first. The new
+/// archive replaces the old one and links back to it; later encryptions of
+/// the vault keep the new compression and hash.
+#[tauri::command]
+#[specta::specta]
+#[instrument(skip(input), fields(vault_id = %input.vault_id, archive_id = %input.archive_id))]
+pub async fn migrate_archive(
+    input: MigrateArchiveInput,
+) -> CommandResponse<ArchiveMigrationReport> {
+    input.validate()?;
+    check_target(&input.target_parameters)?;
+    let operation =
+        begin_operation(OperationKind::Encryption).map_err(|e| Box::new(CommandError::from(e)))?;
+    operation.set_vault(&input.vault_id);
+    reclaim_storage().await;
+
+    let MigrateArchiveInput {
+        vault_id,
+        archive_id,
+        target_parameters,
+        key_id,
+        passphrase,
+        prune_original,
+    } = input;
+
+    let report = CryptoManager::new()
+        .migrate_archive(
+            &vault_id,
+            &archive_id,
+            &target_parameters,
+            &key_id,
+            SecretString::from(passphrase),
+            prune_original,
+        )
+        .await
+        .map_err(|e| {
+            let (code, guidance) = match &e {
+                CryptoError::ArchiveImmutable { .. } => (
+                    ErrorCode::ArchiveImmutable,
+                    "Clear the archive's immutable flag before migrating it",
+                ),
+                CryptoError::FileNotFound(_) => (
+                    ErrorCode::FileNotFound,
+                    "Only the newest encryption of an archive can be migrated",
+                ),
+                CryptoError::InvalidKey(_) => (
+                    ErrorCode::KeyNotFound,
+                    "Select a key registered with this vault",
+                ),
+                CryptoError::InvalidInput(_) => (
+                    ErrorCode::InvalidInput,
+                    "Nothing was changed. Check the archive has a manifest and its files are intact",
+                ),
+                CryptoError::EncryptionFailed(_) => (
+                    ErrorCode::EncryptionFailed,
+                    "The original archive was left unchanged. Try again",
+                ),
+                _ => (
+                    ErrorCode::DecryptionFailed,
+                    "Check your passphrase or PIN and try again",
+                ),
+            };
+            Box::new(CommandError::operation(code, e.to_string()).with_recovery_guidance(guidance))
+        })?;
+
+    info!(
+        original = %report.original_archive_id,
+        archive_id = %report.archive_id,
+        verified_files = report.verified_files,
+        "Archive migrated"
+    );
+    Ok(report)
+}
@@ -0,0 +1,113 @@
+//! Archive path remap command
+//!
+//! Thin wrapper following Command → Manager → Service pattern.
+//! Rewrites the paths inside an archive after a source folder was renamed,
+//! as a new archive beside the original.
+
+use crate::commands::reclaim_storage;
+use crate::commands::types::{CommandError, CommandResponse, ErrorCode, ValidateInput};
+use crate::commands::validation::{
+    ExistingVaultId, NonEmpty, NonEmptyId, NonEmptyList, input_rules,
+};
+use crate::prelude::*;
+use crate::services::crypto::domain::models::{ArchiveRemapReport, PathPrefixMapping};
+use crate::services::crypto::{CryptoError, CryptoManager};
+use crate::services::shared::infrastructure::progress::{OperationKind, begin_operation};
+use age::secrecy::SecretString;
+
+/// Input for remapping an archive's paths
+#[derive(Debug, Deserialize, specta::Type)]
+pub struct RemapArchivePathsInput {
+    pub vault_id: String,
+    /// Archive to rewrite; must be current
+    pub archive_id: String,
+    /// Path prefixes to rewrite, e.g. `Taxes` → `Finanzen`
+    pub mappings: Vec<PathPrefixMapping>,
+    pub key_id: String,
+    /// Key passphrase, or the PIN for a YubiKey
+    pub passphrase: String,
+}
+
+input_rules! {
+    RemapArchivePathsInput {
+        vault_id("Vault ID"): [ExistingVaultId],
+        archive_id("Archive ID"): [NonEmptyId],
+        mappings("mapping"): [NonEmptyList],
+        key_id("Key ID"): [NonEmptyId],
+        passphrase("Passphrase"): [NonEmpty],
+    }
+}
+
+/// Re-encrypt a vault's current archive with path prefixes rewritten
+///
+/// Prefixes match whole path components. The mappings are checked against
+/// the manifest and every file against its digest before anything is
+/// written. The new archive is written as `<name>-remapped.age` and links
+/// back to the original, which is left unchanged.
+#[tauri::command]
+#[specta::specta]
+#[instrument(skip(input), fields(vault_id = %input.vault_id, archive_id = %input.archive_id))]
+pub async fn remap_archive_paths(
+    input: RemapArchivePathsInput,
+) -> CommandResponse<ArchiveRemapReport> {
+    input.validate()?;
+    let operation =
+        begin_operation(OperationKind::Encryption).map_err(|e| Box::new(CommandError::from(e)))?;
+    operation.set_vault(&input.vault_id);
+    reclaim_storage().await;
+
+    let RemapArchivePathsInput {
+        vault_id,
+        archive_id,
+        mappings,
+        key_id,
+        passphrase,
+    } = input;
+
+    let report = CryptoManager::new()
+        .remap_archive_paths(
+            &vault_id,
+            &archive_id,
+            &mappings,
+            &key_id,
+            SecretString::from(passphrase),
+        )
+        .await
+        .map_err(|e| {
+            let (code, guidance) = match &e {
+                CryptoError::ArchiveImmutable { .. } => (
+                    ErrorCode::ArchiveImmutable,
+                    "A remapped copy of this archive is marked immutable; clear its flag first",
+                ),
+                CryptoError::FileNotFound(_) => (
+                    ErrorCode::FileNotFound,
+                    "Only the newest encryption of an archive can be remapped",
+                ),
+                CryptoError::InvalidKey(_) => (
+                    ErrorCode::KeyNotFound,
+                    "Select a key registered with this vault",
+                ),
+                CryptoError::InvalidInput(_) => (
+                    ErrorCode::InvalidInput,
+                    "Nothing was changed. Check the mappings match whole folder names in the archive",
+                ),
+                CryptoError::EncryptionFailed(_) => (
+                    ErrorCode::EncryptionFailed,
+                    "The original archive was left unchanged. Try again",
+                ),
+                _ => (
+                    ErrorCode::DecryptionFailed,
+                    "Check your passphrase or PIN and try again",
+                ),
+            };
+            Box::new(CommandError::operation(code, e.to_string()).with_recovery_guidance(guidance))
+        })?;
+
+    info!(
+        original = %report.original_archive_id,
+        archive_id = %report.archive_id,
+        remapped_entries = report.remapped_entries,
+        "Archive paths remapped"
+    );
+    Ok(report)
+}
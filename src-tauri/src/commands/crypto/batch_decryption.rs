@@ -0,0 +1,123 @@
+//! Batch YubiKey decryption command
+//!
+//! Thin wrapper following Command → Manager → Service pattern.
+//! Decrypts several archives of a vault with one YubiKey PIN entry and
+//! reports per-archive progress under a single operation ID.
+
+use crate::commands::types::{CommandError, CommandResponse, ErrorCode, ValidateInput};
+use crate::commands::validation::{
+    ExistingVaultId, NonEmpty, NonEmptyId, NonEmptyList, PathWithinAllowedRoots, input_rules,
+};
+use crate::logging::spawn_blocking;
+use crate::prelude::*;
+use crate::services::crypto::CryptoError;
+use crate::services::crypto::CryptoManager;
+use crate::services::crypto::application::services::{
+    BatchDecryptionOptions, BatchDecryptionReport,
+};
+use crate::services::file::infrastructure::file_operations::PathLimitStrategy;
+use crate::services::shared::infrastructure::progress::{OperationKind, begin_operation};
+use age::secrecy::SecretString;
+use std::path::PathBuf;
+
+/// Input for batch decryption
+#[derive(Debug, Deserialize, specta::Type)]
+pub struct DecryptBatchInput {
+    pub vault_id: String,
+    /// Archives to decrypt, in order (see `list_archives`)
+    pub archive_ids: Vec<String>,
+    /// Each archive is extracted to its own folder inside this one
+    pub output_dir: String,
+    /// YubiKey key used for every archive
+    pub key_id: String,
+    pub pin: String,
+    /// Skip the remaining archives after the first failure (default: false)
+    pub stop_on_error: Option<bool>,
+    /// How to handle paths too long for this platform (default: Fail)
+    pub path_limit_strategy: Option<PathLimitStrategy>,
+}
+
+input_rules! {
+    DecryptBatchInput {
+        vault_id("Vault ID"): [ExistingVaultId],
+        key_id("Key ID"): [NonEmptyId],
+        pin("PIN"): [NonEmpty],
+        output_dir("Output directory"): [PathWithinAllowedRoots],
+        archive_ids("archive"): [NonEmptyList],
+    }
+}
+
+/// Decrypt several archives with one YubiKey unlock
+///
+/// A failed archive is reported and the batch moves on unless
+/// `stop_on_error` is set. Progress for every archive is published under the
+/// returned report's `operation_id`.
+#[tauri::command]
+#[specta::specta]
+#[instrument(skip(input), fields(vault_id = %input.vault_id, archives = input.archive_ids.len()))]
+pub async fn decrypt_batch(input: DecryptBatchInput) -> CommandResponse<BatchDecryptionReport> {
+    input.validate()?;
+    let operation = begin_operation(OperationKind::BatchDecryption)
+        .map_err(|e| Box::new(CommandError::from(e)))?;
+
+    let operation_id = format!("decrypt_batch_{}", chrono::Utc::now().timestamp());
+    operation.set_vault(&input.vault_id);
+    operation.set_progress_id(&operation_id);
+    let options = BatchDecryptionOptions {
+        stop_on_error: input.stop_on_error.unwrap_or(false),
+        path_limit_strategy: input.path_limit_strategy.unwrap_or_default(),
+        ..Default::default()
+    };
+    let DecryptBatchInput {
+        vault_id,
+        archive_ids,
+        output_dir,
+        key_id,
+        pin,
+        ..
+    } = input;
+
+    let report = spawn_blocking({
+        let operation_id = operation_id.clone();
+        move || {
+            CryptoManager::new().decrypt_batch(
+                &operation_id,
+                &vault_id,
+                &archive_ids,
+                &PathBuf::from(output_dir),
+                &key_id,
+                SecretString::from(pin),
+                options,
+            )
+        }
+    })
+    .await
+    .map_err(|e| CommandError::operation(ErrorCode::InternalError, format!("Task error: {e}")))?
+    .map_err(|e| {
+        let (code, guidance) = match &e {
+            CryptoError::InvalidInput(_) => (
+                ErrorCode::InvalidInput,
+                "Check the selected archives belong to this vault and the key is a YubiKey",
+            ),
+            CryptoError::InvalidKey(_) => (
+                ErrorCode::KeyNotFound,
+                "Select a YubiKey registered with this vault",
+            ),
+            _ => (
+                ErrorCode::YubiKeyError,
+                "Make sure your YubiKey is connected and try again",
+            ),
+        };
+        Box::new(CommandError::operation(code, e.to_string()).with_recovery_guidance(guidance))
+    })?;
+
+    info!(
+        operation_id = %operation_id,
+        decrypted = report.decrypted,
+        failed = report.failed,
+        skipped = report.skipped,
+        "Batch decryption completed"
+    );
+
+    Ok(report)
+}
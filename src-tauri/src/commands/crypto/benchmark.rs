@@ -0,0 +1,101 @@
+//! Throughput benchmark commands
+//!
+//! Thin wrappers following Command → Manager → Service pattern. A benchmark
+//! runs on synthetic data only and refuses to start while any encryption,
+//! decryption or maintenance operation is running.
+
+use crate::commands::types::{
+    CommandError, CommandResponse, ErrorCode, ValidateInput, ValidationRule,
+};
+use crate::commands::validation::invalid;
+use crate::logging::spawn_blocking;
+use crate::prelude::*;
+use crate::services::crypto::CryptoManager;
+use crate::services::crypto::domain::models::{BenchmarkProfile, BenchmarkResult, BenchmarkSizes};
+use crate::services::shared::infrastructure::progress::{OperationKind, begin_exclusive_operation};
+use crate::types::ByteSize;
+
+/// Largest synthetic data set accepted; the archive is held in memory
+const MAX_BENCHMARK_SIZE: ByteSize = ByteSize(4 * 1024 * 1024 * 1024);
+const MAX_BENCHMARK_FILES: u32 = 100_000;
+
+/// Input for a benchmark run
+#[derive(Debug, Deserialize, specta::Type)]
+pub struct RunBenchmarkInput {
+    pub profile: BenchmarkProfile,
+    /// Overrides the profile's data set
+    #[serde(default)]
+    pub sizes: Option<BenchmarkSizes>,
+}
+
+impl ValidateInput for RunBenchmarkInput {
+    fn validate(&self) -> Result<(), Box<CommandError>> {
+        let Some(sizes) = self.sizes else {
+            return Ok(());
+        };
+
+        if sizes.total() == ByteSize::ZERO {
+            return Err(Box::new(invalid(
+                "sizes",
+                ValidationRule::PositiveByteSize,
+                "Benchmark sizes must include at least one non-empty file",
+            )));
+        }
+        if sizes.total() > MAX_BENCHMARK_SIZE {
+            return Err(Box::new(invalid(
+                "sizes",
+                ValidationRule::InRange,
+                format!("Benchmark data can't exceed {}", MAX_BENCHMARK_SIZE),
+            )));
+        }
+        if sizes.small_file_count > MAX_BENCHMARK_FILES {
+            return Err(Box::new(invalid(
+                "sizes",
+                ValidationRule::InRange,
+                format!("Benchmark can't generate more than {MAX_BENCHMARK_FILES} files"),
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Measure archive, encryption and decryption throughput on this machine
+///
+/// The result is also added to the local benchmark history.
+#[tauri::command]
+#[specta::specta]
+#[instrument(skip(input), fields(profile = ?input.profile))]
+pub async fn run_benchmark(input: RunBenchmarkInput) -> CommandResponse<BenchmarkResult> {
+    input.validate()?;
+    let _operation = begin_exclusive_operation(OperationKind::Benchmark)
+        .map_err(|e| Box::new(CommandError::from(e)))?;
+
+    let sizes = input
+        .sizes
+        .unwrap_or_else(|| BenchmarkSizes::for_profile(input.profile));
+    let profile = input.profile;
+
+    spawn_blocking(move || CryptoManager::new().run_benchmark(profile, sizes))
+        .await
+        .map_err(|e| CommandError::operation(ErrorCode::InternalError, format!("Task error: {e}")))?
+        .map_err(|e| {
+            error!(error = %e, "Benchmark failed");
+            Box::new(
+                CommandError::operation(ErrorCode::InternalError, e.to_string())
+                    .with_recovery_guidance("Check there is enough free disk space and try again"),
+            )
+        })
+}
+
+/// Recorded benchmark results, oldest first
+#[tauri::command]
+#[specta::specta]
+#[instrument]
+pub async fn get_benchmark_history() -> CommandResponse<Vec<BenchmarkResult>> {
+    CryptoManager::new().benchmark_history().map_err(|e| {
+        Box::new(CommandError::operation(
+            ErrorCode::StorageFailed,
+            e.to_string(),
+        ))
+    })
+}
@@ -0,0 +1,139 @@
+//! Archive browsing commands
+//!
+//! Thin wrapper following Command → Manager → Service pattern.
+//! Opens a decrypted archive read-only on a localhost URL without extracting
+//! it, and closes it again.
+
+use crate::commands::types::{CommandError, CommandResponse, ErrorCode, ValidateInput};
+use crate::commands::validation::{ExistingVaultId, NonEmpty, NonEmptyId, input_rules};
+use crate::logging::spawn_blocking;
+use crate::prelude::*;
+use crate::services::crypto::CryptoError;
+use crate::services::crypto::CryptoManager;
+use crate::services::crypto::application::services::BrowseSessionInfo;
+use age::secrecy::SecretString;
+
+/// Input for opening an archive for browsing
+#[derive(Debug, Deserialize, specta::Type)]
+pub struct BrowseArchiveInput {
+    pub vault_id: String,
+    /// Archive to open (see `list_archives`); must be its newest revision
+    pub archive_id: String,
+    pub key_id: String,
+    /// Key passphrase, or the PIN for a YubiKey
+    pub passphrase: String,
+}
+
+input_rules! {
+    BrowseArchiveInput {
+        vault_id("Vault ID"): [ExistingVaultId],
+        archive_id("Archive ID"): [NonEmptyId],
+        key_id("Key ID"): [NonEmptyId],
+        passphrase("Passphrase"): [NonEmpty],
+    }
+}
+
+/// Input for closing a browse session
+#[derive(Debug, Deserialize, specta::Type)]
+pub struct StopBrowsingInput {
+    pub session_id: String,
+}
+
+input_rules! {
+    StopBrowsingInput {
+        session_id("Session ID"): [NonEmptyId],
+    }
+}
+
+/// Response from closing a browse session
+#[derive(Debug, Serialize, specta::Type)]
+pub struct StopBrowsingResponse {
+    /// False if the session had already closed (stopped or idle timeout)
+    pub stopped: bool,
+}
+
+/// Open a decrypted archive read-only on a localhost URL
+///
+/// The returned URL carries an access token and only works on this machine.
+/// Nothing is extracted; the snapshot is wiped when browsing stops or after
+/// the idle timeout.
+#[tauri::command]
+#[specta::specta]
+#[instrument(skip(input), fields(vault_id = %input.vault_id, archive_id = %input.archive_id))]
+pub async fn browse_archive(input: BrowseArchiveInput) -> CommandResponse<BrowseSessionInfo> {
+    input.validate()?;
+
+    let BrowseArchiveInput {
+        vault_id,
+        archive_id,
+        key_id,
+        passphrase,
+    } = input;
+
+    let session = spawn_blocking(move || {
+        CryptoManager::new().browse_archive(
+            &vault_id,
+            &archive_id,
+            &key_id,
+            SecretString::from(passphrase),
+        )
+    })
+    .await
+    .map_err(|e| CommandError::operation(ErrorCode::InternalError, format!("Task error: {e}")))?
+    .map_err(|e| {
+        let (code, guidance) = match &e {
+            CryptoError::AppTooOld { .. } => (
+                ErrorCode::AppVersionTooOld,
+                "Install the latest version of Barqly Vault to open this archive",
+            ),
+            CryptoError::KeyMediaNotPresent { .. } => (
+                ErrorCode::KeyMediaNotPresent,
+                "Insert the drive holding this key file, then try again",
+            ),
+            CryptoError::OperationInProgress => (
+                ErrorCode::InvalidInput,
+                "This vault is already open for browsing. Stop that session first",
+            ),
+            CryptoError::FileNotFound(_) => (
+                ErrorCode::FileNotFound,
+                "Only the newest encryption of an archive can be browsed",
+            ),
+            CryptoError::FileTooLarge(_) => (
+                ErrorCode::InvalidInput,
+                "This archive is too large to browse. Decrypt it to a folder instead",
+            ),
+            CryptoError::InvalidKey(_) => (
+                ErrorCode::KeyNotFound,
+                "Select a key registered with this vault",
+            ),
+            CryptoError::InvalidInput(_) => (
+                ErrorCode::InvalidInput,
+                "Check the selected archive belongs to this vault",
+            ),
+            _ => (
+                ErrorCode::DecryptionFailed,
+                "Check your passphrase or PIN and try again",
+            ),
+        };
+        Box::new(CommandError::operation(code, e.to_string()).with_recovery_guidance(guidance))
+    })?;
+
+    info!(
+        session_id = %session.session_id,
+        file_count = session.file_count,
+        "Archive opened for browsing"
+    );
+
+    Ok(session)
+}
+
+/// Close a browse session and wipe its decrypted snapshot
+#[tauri::command]
+#[specta::specta]
+#[instrument(skip(input), fields(session_id = %input.session_id))]
+pub async fn stop_browsing(input: StopBrowsingInput) -> CommandResponse<StopBrowsingResponse> {
+    input.validate()?;
+
+    let stopped = CryptoManager::new().stop_browsing(&input.session_id);
+    Ok(StopBrowsingResponse { stopped })
+}
@@ -0,0 +1,144 @@
+//! Scheduled cleanup of decrypted output
+//!
+//! Thin wrappers following Command → Manager → Service pattern. Sessions are
+//! created by `decrypt_data` with a `session_ttl_minutes`; these commands let
+//! the user see, postpone or cancel them. A background thread started at app
+//! launch runs overdue cleanups and announces each result with
+//! `CLEANUP_SESSION_EVENT`.
+
+use crate::commands::types::{CommandError, CommandResponse, ErrorCode, ValidateInput};
+use crate::commands::validation::{InRange, NonEmptyId, input_rules};
+use crate::prelude::*;
+use crate::services::crypto::application::services::MAX_SESSION_TTL_MINUTES;
+use crate::services::crypto::domain::models::{CleanupResult, CleanupSessionSummary};
+use crate::services::crypto::{CryptoError, CryptoManager};
+use crate::services::key_management::yubikey::infrastructure::pty::app_handle::get_app_handle;
+use std::time::Duration;
+use tauri::Emitter;
+
+/// App-wide event carrying a `CleanupResult`
+pub const CLEANUP_SESSION_EVENT: &str = "cleanup-session-finished";
+
+/// How often the scheduler looks for overdue sessions
+const CLEANUP_CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Input for postponing a cleanup
+#[derive(Debug, Deserialize, specta::Type)]
+pub struct ExtendCleanupSessionInput {
+    pub session_id: String,
+    /// Minutes added to the deadline (from now, if already overdue)
+    pub minutes: u32,
+}
+
+input_rules! {
+    ExtendCleanupSessionInput {
+        session_id("Session ID"): [NonEmptyId],
+        minutes("Extension"): [InRange { min: 1, max: MAX_SESSION_TTL_MINUTES }],
+    }
+}
+
+/// Input for cancelling a cleanup
+#[derive(Debug, Deserialize, specta::Type)]
+pub struct CancelCleanupSessionInput {
+    pub session_id: String,
+}
+
+input_rules! {
+    CancelCleanupSessionInput {
+        session_id("Session ID"): [NonEmptyId],
+    }
+}
+
+fn cleanup_error(e: CryptoError) -> Box<CommandError> {
+    match e {
+        CryptoError::InvalidInput(message) => Box::new(
+            CommandError::operation(ErrorCode::InvalidInput, message)
+                .with_recovery_guidance("The cleanup may already have run or been cancelled"),
+        ),
+        e => Box::new(CommandError::operation(
+            ErrorCode::InternalError,
+            e.to_string(),
+        )),
+    }
+}
+
+/// Pending cleanups of decrypted output with their deadlines, soonest first
+#[tauri::command]
+#[specta::specta]
+#[instrument]
+pub async fn list_cleanup_sessions() -> CommandResponse<Vec<CleanupSessionSummary>> {
+    CryptoManager::new()
+        .list_cleanup_sessions()
+        .map_err(cleanup_error)
+}
+
+/// Postpone the cleanup of a decrypted folder
+#[tauri::command]
+#[specta::specta]
+#[instrument(skip(input), fields(session_id = %input.session_id))]
+pub async fn extend_cleanup_session(
+    input: ExtendCleanupSessionInput,
+) -> CommandResponse<CleanupSessionSummary> {
+    input.validate()?;
+
+    CryptoManager::new()
+        .extend_cleanup_session(&input.session_id, input.minutes)
+        .map_err(cleanup_error)
+}
+
+/// Keep a decrypted folder; its cleanup won't run
+#[tauri::command]
+#[specta::specta]
+#[instrument(skip(input), fields(session_id = %input.session_id))]
+pub async fn cancel_cleanup_session(input: CancelCleanupSessionInput) -> CommandResponse<()> {
+    input.validate()?;
+
+    CryptoManager::new()
+        .cancel_cleanup_session(&input.session_id)
+        .map_err(cleanup_error)
+}
+
+/// Run overdue cleanups now and then every minute for the life of the app
+///
+/// Sessions that fell due while the app was closed run on launch.
+pub fn start_cleanup_scheduler() {
+    let spawned = std::thread::Builder::new()
+        .name("cleanup-sessions".to_string())
+        .spawn(|| {
+            loop {
+                run_due_cleanups();
+                std::thread::sleep(CLEANUP_CHECK_INTERVAL);
+            }
+        });
+    if let Err(e) = spawned {
+        warn!(error = %e, "Failed to start cleanup scheduler");
+    }
+}
+
+fn run_due_cleanups() {
+    let results = match CryptoManager::new().run_due_cleanups() {
+        Ok(results) => results,
+        Err(e) => {
+            warn!(error = %e, "Failed to run scheduled cleanups");
+            return;
+        }
+    };
+    for result in results {
+        notify(&result);
+    }
+}
+
+fn notify(result: &CleanupResult) {
+    if result.is_warning() {
+        warn!(
+            session_id = %result.session_id,
+            outcome = ?result.outcome,
+            "Cleanup needs attention"
+        );
+    }
+    if let Some(app) = get_app_handle()
+        && let Err(e) = app.emit(CLEANUP_SESSION_EVENT, result)
+    {
+        warn!(session_id = %result.session_id, error = %e, "Failed to emit cleanup event");
+    }
+}
@@ -3,13 +3,32 @@
 //! Thin wrapper following Command → Manager → Service pattern.
 //! Handles input validation, progress tracking, and response formatting.
 
+use crate::commands::reclaim_storage;
 use crate::commands::types::{
-    CommandError, CommandResponse, ErrorCode, ErrorHandler, ProgressManager, ValidateInput,
-    ValidationHelper,
+    CommandError, CommandResponse, ErrorCode, IoPriority, ProgressManager, ValidateInput,
+    ValidationRule,
 };
+use crate::commands::validation::{
+    ExistingFile, ExistingVaultId, InRange, NonEmpty, NonEmptyId, PathWithinAllowedRoots,
+    input_rules, invalid,
+};
+use crate::commands::vault::{refresh_onboarding, run_operation_hooks};
 use crate::constants::*;
 use crate::prelude::*;
-use crate::services::crypto::CryptoManager;
+use crate::services::crypto::application::services::{
+    DecryptOptions, KeyRecipientCheck, MAX_SESSION_TTL_MINUTES,
+};
+use crate::services::crypto::domain::models::{CleanupSessionSummary, TimingBreakdown};
+use crate::services::crypto::{CryptoError, CryptoManager, ManifestSource};
+use crate::services::file::infrastructure::file_operations::{
+    FidelityReport, OwnershipPolicy, PathLimitStrategy, RestoreFilter,
+};
+use crate::services::key_management::shared::KeyRegistryService;
+use crate::services::shared::infrastructure::io::register_operation_priority;
+use crate::services::shared::infrastructure::progress::{OperationKind, begin_operation};
+use crate::services::vault::application::services::ManifestDiscrepancy;
+use crate::services::vault::domain::models::HookEvent;
+use crate::services::vault::infrastructure::persistence::ManifestSignatureCheck;
 use age::secrecy::SecretString;
 use tauri::Window;
 
@@ -21,6 +40,21 @@ pub struct DecryptDataInput {
     pub passphrase: String,
     pub output_dir: Option<String>, // Optional - backend generates default if not provided
     pub force_overwrite: Option<bool>, // NEW - for user confirmation to overwrite
+    /// How to handle paths too long for this platform (default: Fail)
+    pub path_limit_strategy: Option<PathLimitStrategy>,
+    /// Who owns extracted files on Unix (default: CurrentUser)
+    pub ownership_policy: Option<OwnershipPolicy>,
+    /// Restore only these paths or content types (default: everything)
+    pub restore_filter: Option<RestoreFilter>,
+    /// Run the file I/O at background priority (default: the app setting)
+    #[serde(default)]
+    pub io_priority: Option<IoPriority>,
+    /// Securely delete the extracted files after this many minutes
+    #[serde(default)]
+    pub session_ttl_minutes: Option<u32>,
+    /// Send a YubiKey PIN even if only one attempt is left
+    #[serde(default)]
+    pub accept_last_attempt: Option<bool>,
 }
 
 /// Result of decryption operation
@@ -32,27 +66,65 @@ pub struct DecryptionResult {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub external_manifest_restored: Option<bool>,
     pub output_exists: bool, // NEW - for conflict dialog
+    /// Entries renamed to fit platform path limits
+    pub renamed_paths: Vec<RenamedPath>,
+    /// Manifest used for the archive: embedded copy, external sidecar, or none
+    pub manifest_source: ManifestSource,
+    /// Fields where the external manifest disagrees with the embedded one
+    pub manifest_discrepancies: Vec<ManifestDiscrepancy>,
+    /// Signature check of the external manifest, when there is one
+    pub manifest_signature: Option<ManifestSignatureCheck>,
+    /// Files whose recorded ownership could not be restored
+    pub fidelity: FidelityReport,
+    /// Files left out by the restore filter
+    pub skipped_by_filter: usize,
+    /// Files kept from an interrupted earlier restore into the same folder
+    pub resumed_files: usize,
+    /// Patches laid over the archive, newest winning
+    pub patches_applied: usize,
+    /// Recorded patches that weren't beside the archive, so weren't applied
+    pub missing_patches: Vec<String>,
+    /// Scheduled deletion of the extracted files, when a TTL was given
+    pub cleanup_session: Option<CleanupSessionSummary>,
+    /// Time and bytes per pipeline phase
+    pub timing_breakdown: TimingBreakdown,
 }
 
-impl ValidateInput for DecryptDataInput {
-    fn validate(&self) -> Result<(), Box<CommandError>> {
-        ValidationHelper::validate_not_empty(&self.encrypted_file, "Encrypted file path")?;
-        ValidationHelper::validate_not_empty(&self.key_id, "Key ID")?;
-        ValidationHelper::validate_not_empty(&self.passphrase, "Passphrase")?;
-
-        // If custom output_dir provided, validate it's safe
-        if let Some(ref dir) = self.output_dir {
-            ValidationHelper::validate_not_empty(dir, "Output directory")?;
-            // Validate it's within safe directories (Documents, home)
-            ValidationHelper::validate_safe_user_path(dir)?;
-        }
+/// Archive entry that was written under a shortened name
+#[derive(Debug, Serialize, specta::Type)]
+pub struct RenamedPath {
+    pub original_path: String,
+    pub extracted_path: String,
+}
 
-        // Validate encrypted file exists and is a file
-        ValidationHelper::validate_path_exists(&self.encrypted_file, "Encrypted file")?;
-        ValidationHelper::validate_is_file(&self.encrypted_file, "Encrypted file")?;
+fn check_restore_filter(input: &DecryptDataInput) -> Result<(), Box<CommandError>> {
+    if let Some(filter) = &input.restore_filter
+        && let Some(pattern) = filter.content_types.iter().find(|t| !t.contains('/'))
+    {
+        return Err(Box::new(
+            invalid(
+                "restore_filter",
+                ValidationRule::Format,
+                format!("Invalid content type filter '{pattern}'"),
+            )
+            .with_recovery_guidance("Use a form like image/* or application/pdf"),
+        ));
+    }
+    Ok(())
+}
 
-        Ok(())
+// Existence of the archive is checked last so a missing key or passphrase is
+// reported first
+input_rules! {
+    DecryptDataInput {
+        encrypted_file("Encrypted file path"): [NonEmpty],
+        key_id("Key ID"): [NonEmptyId],
+        passphrase("Passphrase"): [NonEmpty],
+        output_dir("Output directory"): [PathWithinAllowedRoots],
+        session_ttl_minutes("Session TTL"): [InRange { min: 1, max: MAX_SESSION_TTL_MINUTES }],
+        encrypted_file("Encrypted file"): [ExistingFile],
     }
+    then check_restore_filter
 }
 
 /// Decrypt files with progress streaming - delegates to DecryptionOrchestrationService
@@ -64,13 +136,18 @@ pub async fn decrypt_data(
     _window: Window,
 ) -> CommandResponse<DecryptionResult> {
     // Validate input
-    input
-        .validate()
-        .map_err(|e| ErrorHandler::new().handle_validation_error("input", &e.message))?;
+    input.validate()?;
+    let operation =
+        begin_operation(OperationKind::Decryption).map_err(|e| Box::new(CommandError::from(e)))?;
+    reclaim_storage().await;
 
     // Initialize progress manager
     let operation_id = format!("decrypt_{}", chrono::Utc::now().timestamp());
-    let mut progress_manager = ProgressManager::new(operation_id.clone(), PROGRESS_TOTAL_WORK);
+    operation.set_progress_id(&operation_id);
+    let registration =
+        register_operation_priority(&operation_id, super::resolve_io_priority(input.io_priority));
+    let mut progress_manager = ProgressManager::new(operation_id.clone(), PROGRESS_TOTAL_WORK)
+        .with_io_priority(registration.priority());
 
     info!(
         encrypted_file = %input.encrypted_file,
@@ -99,17 +176,50 @@ pub async fn decrypt_data(
             SecretString::from(input.passphrase),
             custom_output, // Pass Option<PathBuf>
             force_overwrite,
+            input.path_limit_strategy.unwrap_or_default(),
+            input.ownership_policy.unwrap_or_default(),
+            input.restore_filter.unwrap_or_default(),
+            input.accept_last_attempt.unwrap_or(false),
             &mut progress_manager,
         )
         .await
         .map_err(|e| {
             error!(error = %e, "Decryption failed");
-            Box::new(CommandError::operation(
-                ErrorCode::InternalError,
-                format!("Decryption failed: {}", e),
-            ))
+            if let CryptoError::PinAttemptRefused(refusal) = e {
+                return Box::new(CommandError::from(refusal));
+            }
+            let code = match &e {
+                CryptoError::AppTooOld { .. } => ErrorCode::AppVersionTooOld,
+                CryptoError::KeyMediaNotPresent { .. } => ErrorCode::KeyMediaNotPresent,
+                CryptoError::KeyNotARecipient { .. } => ErrorCode::RecipientMismatch,
+                _ => ErrorCode::InternalError,
+            };
+            let error = CommandError::operation(code, format!("Decryption failed: {}", e));
+            Box::new(match &e {
+                CryptoError::KeyNotARecipient {
+                    matching_key_labels,
+                } => error.with_details(matching_key_labels.join(", ")),
+                _ => error,
+            })
         })?;
 
+    // A panic lock or forced quit ran while decrypting; don't leave its output behind
+    if operation.is_cancelled() {
+        warn!("Decryption cancelled, removing extracted files");
+        if !output.output_exists {
+            let removed = manager
+                .register_cleanup_session(&output.output_dir, &output.extracted_files, 1)
+                .and_then(|session| manager.run_cleanup_session_now(&session.id));
+            if let Err(e) = removed {
+                error!(error = %e, "Failed to remove output of cancelled decryption");
+            }
+        }
+        return Err(Box::new(CommandError::operation(
+            ErrorCode::DecryptionFailed,
+            "Decryption was cancelled by a panic lock or forced quit",
+        )));
+    }
+
     // Update progress for completion
     progress_manager.complete("Decryption completed successfully");
     super::update_global_progress(&operation_id, progress_manager.get_current_update());
@@ -117,9 +227,62 @@ pub async fn decrypt_data(
     info!(
         extracted_files_count = output.extracted_files.len(),
         manifest_verified = output.manifest_verified,
+        manifest_source = ?output.manifest_source,
         "Decryption operation completed successfully"
     );
 
+    if !output.manifest_discrepancies.is_empty() {
+        warn!(
+            discrepancy_count = output.manifest_discrepancies.len(),
+            "External manifest is stale - embedded manifest was used"
+        );
+    }
+
+    // A verified restore proves the key works; record it for reminders and onboarding
+    if output.manifest_verified {
+        if let Err(e) = KeyRegistryService::new().mark_key_used(&input.key_id) {
+            warn!(error = %e, key_id = %input.key_id, "Failed to record key use");
+        }
+        refresh_onboarding().await;
+    }
+
+    // Post-operation hooks of the archive's vault, if it has any here
+    if !output.output_exists
+        && let Some(vault_id) = &output.vault_id
+    {
+        let archive_path = &input.encrypted_file;
+        let status = if output.manifest_verified {
+            "success"
+        } else {
+            "unverified"
+        };
+        run_operation_hooks(
+            HookEvent::DecryptionCompleted,
+            vault_id,
+            archive_path,
+            status,
+        )
+        .await;
+        if !output.manifest_verified {
+            run_operation_hooks(
+                HookEvent::VerificationFailed,
+                vault_id,
+                archive_path,
+                "mismatch",
+            )
+            .await;
+        }
+    }
+
+    // Schedule the extracted tree for deletion; the files stay if this fails
+    let cleanup_session = match input.session_ttl_minutes {
+        Some(minutes) if !output.output_exists => manager
+            .register_cleanup_session(&output.output_dir, &output.extracted_files, minutes)
+            .inspect_err(|e| warn!(error = %e, "Failed to schedule cleanup of decrypted files"))
+            .ok(),
+        _ => None,
+    };
+
     // Convert extracted files to string paths
     let extracted_file_paths: Vec<String> = output
         .extracted_files
@@ -133,5 +296,129 @@ pub async fn decrypt_data(
         manifest_verified: output.manifest_verified,
         external_manifest_restored: output.external_manifest_restored,
         output_exists: output.output_exists, // NEW
+        renamed_paths: output
+            .renamed_paths
+            .iter()
+            .map(|mapping| RenamedPath {
+                original_path: mapping.original_path.to_string_lossy().to_string(),
+                extracted_path: mapping.extracted_path.to_string_lossy().to_string(),
+            })
+            .collect(),
+        manifest_source: output.manifest_source,
+        manifest_discrepancies: output.manifest_discrepancies,
+        manifest_signature: output.manifest_signature,
+        fidelity: output.fidelity,
+        skipped_by_filter: output.skipped_by_filter,
+        resumed_files: output.resumed_files,
+        patches_applied: output.patches_applied,
+        missing_patches: output.missing_patches,
+        cleanup_session,
+        timing_breakdown: output.timing_breakdown,
+    })
+}
+
+/// Input for checking a key against an archive
+#[derive(Debug, Deserialize, specta::Type)]
+pub struct CheckDecryptionKeyInput {
+    pub encrypted_file: String,
+    pub key_id: String,
+}
+
+input_rules! {
+    CheckDecryptionKeyInput {
+        encrypted_file("Encrypted file path"): [NonEmpty],
+        key_id("Key ID"): [NonEmptyId],
+        encrypted_file("Encrypted file"): [ExistingFile],
+    }
+}
+
+/// Check whether the selected key can open an archive before asking for its passphrase
+///
+/// Reads only the archive header. When the key isn't a recipient, the labels
+/// of registered keys that are come back so the UI can suggest them.
+#[tauri::command]
+#[specta::specta]
+#[instrument(skip(input), fields(key_id = %input.key_id))]
+pub async fn check_decryption_key(
+    input: CheckDecryptionKeyInput,
+) -> CommandResponse<KeyRecipientCheck> {
+    input.validate()?;
+
+    CryptoManager::new()
+        .check_key_recipient(&input.encrypted_file, &input.key_id)
+        .map_err(|e| {
+            Box::new(CommandError::operation(
+                ErrorCode::InvalidInput,
+                format!("Could not read the archive header: {}", e),
+            ))
+        })
+}
+
+/// Input for listing how an archive can be opened
+///
+/// Name the archive either by path or by vault and archive ID.
+#[derive(Debug, Deserialize, specta::Type)]
+pub struct GetDecryptOptionsInput {
+    pub encrypted_file: Option<String>,
+    pub vault_id: Option<String>,
+    /// Archive in the vault's index (see `list_archives`)
+    pub archive_id: Option<String>,
+}
+
+fn require_archive(input: &GetDecryptOptionsInput) -> Result<(), Box<CommandError>> {
+    let by_id = input.vault_id.is_some() && input.archive_id.is_some();
+    if input.encrypted_file.is_some() == by_id {
+        return Err(Box::new(invalid(
+            "encrypted_file",
+            ValidationRule::NonEmpty,
+            "Give either the archive's path or its vault and archive ID",
+        )));
+    }
+    Ok(())
+}
+
+input_rules! {
+    GetDecryptOptionsInput {
+        encrypted_file("Encrypted file path"): [NonEmpty],
+        vault_id("Vault ID"): [ExistingVaultId],
+        archive_id("Archive ID"): [NonEmptyId],
+        encrypted_file("Encrypted file"): [ExistingFile],
+    }
+    then require_archive
+}
+
+/// List every key that can open an archive and which are usable right now
+///
+/// Reads only the archive header and checks presence: connected YubiKeys,
+/// mounted key drives and key files on disk. Nothing asks for a PIN or
+/// passphrase, so the UI can call this before offering Decrypt.
+#[tauri::command]
+#[specta::specta]
+#[instrument(skip(input))]
+pub async fn get_decrypt_options(input: GetDecryptOptionsInput) -> CommandResponse<DecryptOptions> {
+    input.validate()?;
+
+    let manager = CryptoManager::new();
+    let options = match input.encrypted_file.as_deref() {
+        Some(encrypted_file) => manager.get_decrypt_options(encrypted_file).await,
+        None => {
+            manager
+                .get_archive_decrypt_options(
+                    input.vault_id.as_deref().unwrap_or_default(),
+                    input.archive_id.as_deref().unwrap_or_default(),
+                )
+                .await
+        }
+    };
+
+    options.map_err(|e| match e {
+        CryptoError::FileNotFound(msg) => Box::new(
+            CommandError::operation(ErrorCode::FileNotFound, msg)
+                .with_recovery_guidance("Choose an archive listed for this vault"),
+        ),
+        e => Box::new(CommandError::operation(
+            ErrorCode::InvalidInput,
+            format!("Could not read the archive header: {}", e),
+        )),
     })
 }
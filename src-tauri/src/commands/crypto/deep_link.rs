@@ -0,0 +1,188 @@
+//! `barqly-vault://` deep link commands
+//!
+//! The OS delivers links to `handle_deep_link`, which queues them and emits
+//! `deep-link-encrypt-requested` when the link named a known vault, or
+//! `deep-link-choose-vault` when the user has to pick one. The UI then calls
+//! `confirm_deep_link` or `dismiss_deep_link`; confirmation runs the same path
+//! as `encrypt_files_multi`, so validation, operation locks and progress apply.
+
+use crate::commands::crypto::encryption::run_encrypt_files_multi;
+use crate::commands::types::{CommandError, CommandResponse, ErrorCode};
+use crate::prelude::*;
+use crate::services::crypto::application::dtos::{
+    EncryptFilesMultiInput, EncryptFilesMultiResponse,
+};
+use crate::services::key_management::yubikey::infrastructure::pty::app_handle::get_app_handle;
+use crate::services::shared::infrastructure::deep_link::{
+    DeepLinkError, DeepLinkNotifier, DeepLinkQueue, PendingDeepLink,
+};
+use crate::services::vault::VaultManager;
+use serde::Deserialize;
+use tauri::Emitter;
+
+/// Event emitted with a `PendingDeepLink` whose vault is known
+pub const DEEP_LINK_ENCRYPT_EVENT: &str = "deep-link-encrypt-requested";
+
+/// Event emitted with a `PendingDeepLink` that needs a vault chosen
+pub const DEEP_LINK_CHOOSE_VAULT_EVENT: &str = "deep-link-choose-vault";
+
+/// Emits deep link events to the main window
+struct AppNotifier;
+
+impl DeepLinkNotifier for AppNotifier {
+    fn present(&self, link: &PendingDeepLink) {
+        let Some(app) = get_app_handle() else {
+            return;
+        };
+        let event = if link.vault_id.is_some() {
+            DEEP_LINK_ENCRYPT_EVENT
+        } else {
+            DEEP_LINK_CHOOSE_VAULT_EVENT
+        };
+        if let Err(e) = app.emit(event, link) {
+            warn!(error = %e, "Failed to emit deep link event");
+        }
+    }
+}
+
+/// Accept a link delivered by the OS
+///
+/// Never panics: a refused link is logged and dropped.
+pub async fn handle_deep_link(link: &str) -> Option<PendingDeepLink> {
+    let vaults = match VaultManager::new().list_vaults().await {
+        Ok(vaults) => vaults,
+        Err(e) => {
+            warn!(error = %e, "Could not list vaults for deep link; asking the user instead");
+            Vec::new()
+        }
+    };
+    let resolve_vault = |requested: &str| {
+        vaults
+            .iter()
+            .find(|vault| vault.id == requested)
+            .or_else(|| {
+                vaults
+                    .iter()
+                    .find(|vault| vault.name.eq_ignore_ascii_case(requested))
+            })
+            .map(|vault| vault.id.clone())
+    };
+
+    match DeepLinkQueue::global().submit(link, resolve_vault, &AppNotifier) {
+        Ok(pending) => {
+            info!(
+                request_id = %pending.request_id,
+                file_count = pending.paths.len(),
+                vault_resolved = pending.vault_id.is_some(),
+                "Accepted deep link"
+            );
+            Some(pending)
+        }
+        Err(e) => {
+            warn!(error = %e, link_len = link.len(), "Rejected deep link");
+            None
+        }
+    }
+}
+
+/// Register the deep link handler with the OS
+///
+/// Also handles the link the app was launched with, if any.
+pub fn register_deep_link_handler(app: &tauri::App) {
+    use tauri_plugin_deep_link::DeepLinkExt;
+
+    // Bundles register the scheme at install time; dev builds need it at runtime
+    #[cfg(any(target_os = "linux", all(debug_assertions, windows)))]
+    if let Err(e) = app.deep_link().register_all() {
+        warn!(error = %e, "Failed to register deep link scheme");
+    }
+
+    let dispatch = |urls: Vec<String>| {
+        for url in urls {
+            tauri::async_runtime::spawn(async move {
+                handle_deep_link(&url).await;
+            });
+        }
+    };
+
+    match app.deep_link().get_current() {
+        Ok(Some(urls)) => dispatch(urls.iter().map(|url| url.to_string()).collect()),
+        Ok(None) => {}
+        Err(e) => warn!(error = %e, "Failed to read launch deep link"),
+    }
+
+    app.deep_link().on_open_url(move |event| {
+        dispatch(event.urls().iter().map(|url| url.to_string()).collect());
+    });
+}
+
+/// Link currently awaiting confirmation, if any
+#[tauri::command]
+#[specta::specta]
+#[instrument]
+pub async fn get_pending_deep_link() -> CommandResponse<Option<PendingDeepLink>> {
+    Ok(DeepLinkQueue::global().current())
+}
+
+/// Input for confirming a deep link
+#[derive(Debug, Deserialize, specta::Type)]
+pub struct ConfirmDeepLinkInput {
+    pub request_id: String,
+    /// Vault chosen by the user; defaults to the vault the link named
+    pub vault_id: Option<String>,
+}
+
+/// Encrypt the files of the pending link, then present the next one
+#[tauri::command]
+#[specta::specta]
+#[instrument(skip_all, fields(request_id = %input.request_id))]
+pub async fn confirm_deep_link(
+    input: ConfirmDeepLinkInput,
+) -> CommandResponse<EncryptFilesMultiResponse> {
+    let queue = DeepLinkQueue::global();
+    let confirmed = queue
+        .confirm(&input.request_id, input.vault_id)
+        .map_err(deep_link_error)?;
+
+    let result = run_encrypt_files_multi(EncryptFilesMultiInput {
+        vault_id: confirmed.vault_id,
+        in_file_paths: confirmed.paths,
+        out_encrypted_file_name: None,
+        out_encrypted_file_path: None,
+        locked_file_policy: None,
+        resilient_source: None,
+        comment: None,
+        io_priority: None,
+        armored_output: None,
+        generate_parity: None,
+        contact_ids: vec![],
+        strict: None,
+    })
+    .await;
+
+    // A link that was busy (another encryption running) stays pending for a retry
+    let busy = matches!(&result, Err(e) if matches!(e.code, ErrorCode::ConcurrentOperation));
+    if !busy && let Err(e) = queue.finish(&confirmed.request_id, &AppNotifier) {
+        warn!(error = %e, "Deep link finished twice");
+    }
+    result
+}
+
+/// Drop the pending link without encrypting, then present the next one
+#[tauri::command]
+#[specta::specta]
+#[instrument]
+pub async fn dismiss_deep_link(request_id: String) -> CommandResponse<()> {
+    DeepLinkQueue::global()
+        .finish(&request_id, &AppNotifier)
+        .map_err(deep_link_error)?;
+    Ok(())
+}
+
+fn deep_link_error(error: DeepLinkError) -> Box<CommandError> {
+    let code = match error {
+        DeepLinkError::NotPending(_) => ErrorCode::OperationNotFound,
+        _ => ErrorCode::InvalidInput,
+    };
+    Box::new(CommandError::operation(code, error.to_string()))
+}
@@ -3,9 +3,18 @@
 //! This module provides Tauri commands that delegate to the crypto service layer
 //! for actual business logic implementation.
 
+use super::{resolve_io_priority, update_global_progress};
+use crate::commands::reclaim_storage;
 use crate::commands::types::{CommandError, CommandResponse, ErrorCode, ValidateInput};
+use crate::commands::vault::{refresh_onboarding, run_operation_hooks};
+use crate::constants::*;
 use crate::prelude::*;
-use crate::services::crypto::CryptoManager;
+use crate::services::crypto::{CryptoError, CryptoManager};
+use crate::services::shared::infrastructure::io::register_operation_priority;
+use crate::services::shared::infrastructure::progress::{
+    OperationKind, ProgressManager, begin_operation,
+};
+use crate::services::vault::domain::models::HookEvent;
 use tauri::Window;
 
 // Re-export DTOs from application layer for Tauri bindings
@@ -18,12 +27,18 @@
 pub async fn encrypt_files(input: EncryptDataInput, _window: Window) -> CommandResponse<String> {
     // Validate input at command layer
     input.validate()?;
+    let _operation =
+        begin_operation(OperationKind::Encryption).map_err(|e| Box::new(CommandError::from(e)))?;
+    reclaim_storage().await;
 
     // Delegate to service layer for business logic
     let manager = CryptoManager::new();
 
     match manager.encrypt_files(input).await {
-        Ok(encrypted_path) => Ok(encrypted_path),
+        Ok(encrypted_path) => {
+            refresh_onboarding().await;
+            Ok(encrypted_path)
+        }
         Err(crypto_error) => {
             // Convert service error to command error
             Err(Box::new(CommandError::operation(
@@ -47,19 +62,71 @@ pub async fn encrypt_files(input: EncryptDataInput, _window: Window) -> CommandR
 pub async fn encrypt_files_multi(
     input: EncryptFilesMultiInput,
     _window: Window,
+) -> CommandResponse<EncryptFilesMultiResponse> {
+    run_encrypt_files_multi(input).await
+}
+
+/// Validation, operation lock and service call behind `encrypt_files_multi`
+///
+/// Shared with other entry points (e.g. confirmed deep links) so they get the
+/// same checks and progress reporting.
+pub(crate) async fn run_encrypt_files_multi(
+    input: EncryptFilesMultiInput,
 ) -> CommandResponse<EncryptFilesMultiResponse> {
     // Validate input at command layer
     input.validate()?;
+    let operation =
+        begin_operation(OperationKind::Encryption).map_err(|e| Box::new(CommandError::from(e)))?;
+    operation.set_vault(&input.vault_id);
+    reclaim_storage().await;
+
+    // Registered so the priority can be switched with set_operation_priority
+    let operation_id = format!("encrypt_{}", chrono::Utc::now().timestamp());
+    operation.set_progress_id(&operation_id);
+    let registration =
+        register_operation_priority(&operation_id, resolve_io_priority(input.io_priority));
+    let mut progress = ProgressManager::new(operation_id, PROGRESS_TOTAL_WORK)
+        .with_io_priority(registration.priority())
+        .with_callback(Box::new(|update| {
+            update_global_progress(&update.operation_id.clone(), update)
+        }));
+    progress.set_progress(PROGRESS_ENCRYPT_INIT, "Encrypting files...");
 
     // Delegate to service layer for business logic
     let manager = CryptoManager::new();
 
-    match manager.encrypt_files_multi(input).await {
-        Ok(response) => Ok(response),
+    let vault_id = input.vault_id.clone();
+    match manager
+        .encrypt_files_multi(input, registration.priority())
+        .await
+    {
+        Ok(response) => {
+            progress.complete("Encryption completed successfully");
+            refresh_onboarding().await;
+            let status = if response.complete {
+                "success"
+            } else {
+                "incomplete"
+            };
+            run_operation_hooks(
+                HookEvent::EncryptionCompleted,
+                &vault_id,
+                &response.encrypted_file_path,
+                status,
+            )
+            .await;
+            Ok(response)
+        }
         Err(crypto_error) => {
             // Convert service error to command error
+            let code = match crypto_error {
+                CryptoError::ArchiveImmutable { .. } => ErrorCode::ArchiveImmutable,
+                CryptoError::SelectionOverlapsAppData { .. } => ErrorCode::SelectionOverlapsAppData,
+                CryptoError::CrossVaultNameCollision { .. } => ErrorCode::CrossVaultNameCollision,
+                _ => ErrorCode::EncryptionFailed,
+            };
             Err(Box::new(CommandError::operation(
-                ErrorCode::EncryptionFailed,
+                code,
                 crypto_error.to_string(),
             )))
         }
@@ -4,17 +4,26 @@
 //! after decryption to ensure data integrity.
 
 use crate::commands::types::{
-    CommandError, CommandResponse, ErrorCode, ProgressManager, ValidateInput, ValidationHelper,
+    ByteSize, CommandError, CommandResponse, ErrorCode, ProgressManager, ValidateInput,
 };
+use crate::commands::validation::{ExistingDirectory, ExistingFile, NonEmpty, input_rules};
 use crate::constants::*;
 use crate::prelude::*;
 use crate::services::file::FileManager;
+use crate::services::file::infrastructure::file_operations::{
+    UnexpectedEntries, VerificationStrictness,
+};
+use crate::services::vault::infrastructure::persistence::{ManifestSignatureCheck, ManifestSigner};
+use std::path::Path;
 
 /// Input for manifest verification command
 #[derive(Debug, Deserialize, specta::Type)]
 pub struct VerifyManifestInput {
     pub manifest_path: String,
     pub extracted_files_dir: String,
+    /// `ExactMatch` also fails on files the manifest doesn't list
+    #[serde(default)]
+    pub strictness: VerificationStrictness,
 }
 
 /// Response from manifest verification command
@@ -23,32 +32,20 @@ pub struct VerifyManifestResponse {
     pub is_valid: bool,
     pub message: String,
     pub file_count: usize,
-    pub total_size: u64,
+    pub total_size: ByteSize,
+    /// Signature check of the manifest; `None` if it couldn't be read
+    pub signature: Option<ManifestSignatureCheck>,
+    pub strictness: VerificationStrictness,
+    /// Found in the directory but not in the manifest; `ExactMatch` only
+    pub unexpected: Option<UnexpectedEntries>,
 }
 
-impl ValidateInput for VerifyManifestInput {
-    fn validate(&self) -> Result<(), Box<CommandError>> {
-        ValidationHelper::validate_not_empty(&self.manifest_path, "Manifest path")?;
-        ValidationHelper::validate_not_empty(
-            &self.extracted_files_dir,
-            "Extracted files directory",
-        )?;
-
-        // Validate manifest path exists and is a file
-        ValidationHelper::validate_path_exists(&self.manifest_path, "Manifest file")?;
-        ValidationHelper::validate_is_file(&self.manifest_path, "Manifest file")?;
-
-        // Validate extracted files directory exists and is a directory
-        ValidationHelper::validate_path_exists(
-            &self.extracted_files_dir,
-            "Extracted files directory",
-        )?;
-        ValidationHelper::validate_is_directory(
-            &self.extracted_files_dir,
-            "Extracted files directory",
-        )?;
-
-        Ok(())
+input_rules! {
+    VerifyManifestInput {
+        manifest_path("Manifest path"): [NonEmpty],
+        extracted_files_dir("Extracted files directory"): [NonEmpty],
+        manifest_path("Manifest file"): [ExistingFile],
+        extracted_files_dir("Extracted files directory"): [ExistingDirectory],
     }
 }
 
@@ -86,23 +83,56 @@ pub async fn verify_manifest(
         .verify_manifest(
             input.manifest_path.clone(),
             input.extracted_files_dir.clone(),
+            input.strictness,
         )
         .await
     {
-        Ok(is_valid) => {
+        Ok(unexpected) => {
             progress_manager.complete("Manifest verification completed");
 
-            info!(is_valid, "Manifest verification completed");
+            // Comes from the index; only manifests saved before it are rescanned
+            let summary = manager.manifest_summary(&input.manifest_path).ok();
+
+            // Matching hashes mean nothing if the manifest itself was edited
+            let signature = ManifestSigner::from_keys_dir()
+                .and_then(|signer| signer.verify_file(Path::new(&input.manifest_path)))
+                .map_err(|e| warn!(error = %e, "Manifest signature not checked"))
+                .ok();
+            let signature_ok = signature.as_ref().is_none_or(|s| s.is_acceptable());
+            let exact = unexpected.as_ref().is_none_or(|u| u.is_empty());
+            let is_valid = exact && signature_ok;
+
+            info!(
+                is_valid,
+                unexpected = unexpected.as_ref().map_or(0, |u| u.count()),
+                signature = ?signature.as_ref().map(|s| s.status),
+                "Manifest verification completed"
+            );
+
+            let mut message = if is_valid {
+                "Manifest verification successful".to_string()
+            } else {
+                "Manifest verification failed".to_string()
+            };
+            if let Some(u) = unexpected.as_ref().filter(|u| !u.is_empty()) {
+                message = format!(
+                    "{message}. Found {} entries the manifest doesn't list ({})",
+                    u.count(),
+                    u.total_size
+                );
+            }
+            if let Some(notice) = signature.as_ref().and_then(|s| s.notice()) {
+                message = format!("{message}. {notice}");
+            }
 
             Ok(VerifyManifestResponse {
                 is_valid,
-                message: if is_valid {
-                    "Manifest verification successful".to_string()
-                } else {
-                    "Manifest verification failed".to_string()
-                },
-                file_count: 0,
-                total_size: 0,
+                message,
+                file_count: summary.map_or(0, |s| s.entry_count),
+                total_size: summary.map_or(ByteSize::ZERO, |s| ByteSize(s.total_size)),
+                signature,
+                strictness: input.strictness,
+                unexpected,
             })
         }
         Err(e) => {
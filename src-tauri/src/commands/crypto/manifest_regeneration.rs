@@ -0,0 +1,94 @@
+//! External manifest regeneration command
+//!
+//! Thin wrapper following Command → Manager → Service pattern.
+//! Rebuilds the external manifest from the copy embedded in an encrypted vault,
+//! for when the sidecar was left behind or has gone stale.
+
+use crate::commands::types::{CommandError, CommandResponse, ErrorCode, ValidateInput};
+use crate::commands::validation::{ExistingFile, NonEmpty, NonEmptyId, input_rules};
+use crate::prelude::*;
+use crate::services::crypto::{CryptoError, CryptoManager};
+use crate::services::vault::application::services::ManifestDiscrepancy;
+use age::secrecy::SecretString;
+
+/// Input for external manifest regeneration
+#[derive(Debug, Deserialize, specta::Type)]
+pub struct RegenerateExternalManifestInput {
+    pub encrypted_file: String,
+    pub key_id: String,
+    pub passphrase: String,
+}
+
+/// Result of external manifest regeneration
+#[derive(Debug, Serialize, specta::Type)]
+pub struct RegenerateExternalManifestResponse {
+    pub manifest_path: String,
+    pub vault_id: String,
+    /// Whether an external manifest existed before regeneration
+    pub previous_existed: bool,
+    /// Fields that differed in the replaced external manifest
+    pub replaced_discrepancies: Vec<ManifestDiscrepancy>,
+}
+
+input_rules! {
+    RegenerateExternalManifestInput {
+        encrypted_file("Encrypted file path"): [NonEmpty],
+        key_id("Key ID"): [NonEmptyId],
+        passphrase("Passphrase"): [NonEmpty],
+        encrypted_file("Encrypted file"): [ExistingFile],
+    }
+}
+
+/// Rewrite the external manifest from the manifest embedded in an encrypted vault
+#[tauri::command]
+#[specta::specta]
+#[instrument(skip(input), fields(key_id = %input.key_id))]
+pub async fn regenerate_external_manifest(
+    input: RegenerateExternalManifestInput,
+) -> CommandResponse<RegenerateExternalManifestResponse> {
+    input.validate()?;
+
+    let manager = CryptoManager::new();
+    let result = manager
+        .regenerate_external_manifest(
+            &input.encrypted_file,
+            &input.key_id,
+            SecretString::from(input.passphrase),
+        )
+        .map_err(|e| {
+            error!(error = %e, "External manifest regeneration failed");
+            let (code, guidance) = match &e {
+                CryptoError::InvalidInput(_) => (
+                    ErrorCode::ManifestInvalid,
+                    "This archive has no embedded manifest. Re-encrypt the vault to embed one.",
+                ),
+                CryptoError::KeyMediaNotPresent { .. } => (
+                    ErrorCode::KeyMediaNotPresent,
+                    "Insert the drive holding this key file, then try again",
+                ),
+                CryptoError::IoError(_) => (
+                    ErrorCode::StorageFailed,
+                    "Check that the application data directory is writable",
+                ),
+                _ => (
+                    ErrorCode::DecryptionFailed,
+                    "Check the selected key and passphrase",
+                ),
+            };
+            Box::new(CommandError::operation(code, e.to_string()).with_recovery_guidance(guidance))
+        })?;
+
+    info!(
+        vault_id = %result.vault_id,
+        previous_existed = result.previous_existed,
+        discrepancy_count = result.replaced_discrepancies.len(),
+        "External manifest regenerated"
+    );
+
+    Ok(RegenerateExternalManifestResponse {
+        manifest_path: result.manifest_path.to_string_lossy().to_string(),
+        vault_id: result.vault_id,
+        previous_existed: result.previous_existed,
+        replaced_discrepancies: result.replaced_discrepancies,
+    })
+}
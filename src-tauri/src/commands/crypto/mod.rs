@@ -3,24 +3,73 @@
 //! This module provides cryptographic operations for encryption, decryption,
 //! manifest verification, and vault analysis. For passphrase key operations, see commands::passphrase.
 
+pub mod archive_migration;
+pub mod archive_path_remap;
+pub mod batch_decryption;
+pub mod benchmark;
+pub mod browse;
+pub mod cleanup_sessions;
 pub mod decryption;
+pub mod deep_link;
 pub mod encryption;
 pub mod manifest;
+pub mod manifest_regeneration;
+pub mod panic_lock;
 pub mod progress;
+pub mod recovery_decryption;
+pub mod recovery_simulation;
+pub mod salvage;
+pub mod tool_independence;
 pub mod vault_analysis;
 
-pub use decryption::{DecryptDataInput, DecryptionResult, decrypt_data};
+pub use archive_migration::{
+    MigrateArchiveInput, PlanParameterMigrationInput, migrate_archive, plan_parameter_migration,
+};
+pub use archive_path_remap::{RemapArchivePathsInput, remap_archive_paths};
+pub use batch_decryption::{DecryptBatchInput, decrypt_batch};
+pub use benchmark::{RunBenchmarkInput, get_benchmark_history, run_benchmark};
+pub use browse::{
+    BrowseArchiveInput, StopBrowsingInput, StopBrowsingResponse, browse_archive, stop_browsing,
+};
+pub use cleanup_sessions::{
+    CLEANUP_SESSION_EVENT, CancelCleanupSessionInput, ExtendCleanupSessionInput,
+    cancel_cleanup_session, extend_cleanup_session, list_cleanup_sessions, start_cleanup_scheduler,
+};
+pub use decryption::{
+    CheckDecryptionKeyInput, DecryptDataInput, DecryptionResult, GetDecryptOptionsInput,
+    check_decryption_key, decrypt_data, get_decrypt_options,
+};
+pub use deep_link::{
+    ConfirmDeepLinkInput, confirm_deep_link, dismiss_deep_link, get_pending_deep_link,
+    handle_deep_link, register_deep_link_handler,
+};
 pub use encryption::{
     EncryptDataInput, EncryptFilesMultiInput, EncryptFilesMultiResponse, encrypt_files,
     encrypt_files_multi,
 };
 pub use manifest::{VerifyManifestInput, VerifyManifestResponse, verify_manifest};
+pub use manifest_regeneration::{
+    RegenerateExternalManifestInput, RegenerateExternalManifestResponse,
+    regenerate_external_manifest,
+};
+pub use panic_lock::{PanicLockInput, panic_lock, preview_panic_lock};
+pub(crate) use progress::resolve_io_priority;
 pub use progress::{
     EncryptionStatus, EncryptionStatusResponse, GetEncryptionStatusInput, GetProgressInput,
-    GetProgressResponse, get_encryption_status, get_progress,
+    GetProgressResponse, get_encryption_status, get_progress, set_default_io_priority,
+    set_operation_priority,
+};
+pub use recovery_decryption::{
+    DecryptWithRecoverySharesInput, RecoveryDecryptionResult, decrypt_with_recovery_shares,
+};
+pub use recovery_simulation::{
+    SimulateRecoveryInput, SimulationCredentialsInput, simulate_recovery,
 };
+pub use salvage::{AssessSalvageInput, SalvageDecryptInput, assess_salvage, salvage_decrypt};
+pub use tool_independence::{VerifyToolIndependenceInput, verify_tool_independence};
 pub use vault_analysis::{
-    AnalyzeEncryptedVaultRequest, AnalyzeEncryptedVaultResponse, analyze_encrypted_vault,
+    AnalyzeEncryptedVaultRequest, AnalyzeEncryptedVaultResponse, ContentTypeSummary,
+    analyze_encrypted_vault,
 };
 
 // Re-export global progress functions from infrastructure layer
@@ -0,0 +1,66 @@
+//! Panic lock commands
+//!
+//! Thin wrappers following Command → Manager → Service pattern. The UI first
+//! shows `preview_panic_lock` so the user sees what will be cleared, then
+//! calls `panic_lock` with the typed confirmation.
+
+use crate::commands::clipboard::TauriClipboard;
+use crate::commands::types::{CommandError, CommandResponse, ErrorCode};
+use crate::logging::spawn_blocking;
+use crate::prelude::*;
+use crate::services::crypto::domain::models::{PanicLockPreview, PanicLockReport};
+use crate::services::crypto::{CryptoError, CryptoManager};
+use crate::services::vault::application::services::ClipboardBackend;
+
+/// Input for a panic lock
+#[derive(Debug, Deserialize, specta::Type)]
+pub struct PanicLockInput {
+    /// The user must type "LOCK"
+    pub confirmation: Option<String>,
+}
+
+fn panic_lock_error(e: CryptoError) -> Box<CommandError> {
+    match e {
+        CryptoError::InvalidInput(message) => Box::new(
+            CommandError::operation(ErrorCode::InvalidInput, message)
+                .with_recovery_guidance("Type LOCK exactly to confirm"),
+        ),
+        e => Box::new(CommandError::operation(
+            ErrorCode::InternalError,
+            e.to_string(),
+        )),
+    }
+}
+
+/// What a panic lock would clear right now
+#[tauri::command]
+#[specta::specta]
+#[instrument]
+pub async fn preview_panic_lock() -> CommandResponse<PanicLockPreview> {
+    CryptoManager::new()
+        .preview_panic_lock()
+        .map_err(panic_lock_error)
+}
+
+/// Wipe cached sensitive state: cancel running operations, close browse
+/// sessions, kill PTY sessions, zeroize held recovery shares, clear the
+/// app's copy from the clipboard and run every pending cleanup now
+///
+/// Needs the typed confirmation. Vault data and keys are left alone. Safe
+/// to repeat; the report says what was cleared and what failed.
+#[tauri::command]
+#[specta::specta]
+#[instrument(skip_all)]
+pub async fn panic_lock(input: PanicLockInput) -> CommandResponse<PanicLockReport> {
+    let confirmation = input.confirmation;
+    let clipboard = TauriClipboard::get().ok();
+    spawn_blocking(move || {
+        CryptoManager::new().panic_lock(
+            confirmation.as_deref(),
+            clipboard.as_ref().map(|c| c as &dyn ClipboardBackend),
+        )
+    })
+    .await
+    .map_err(|e| CommandError::operation(ErrorCode::InternalError, format!("Task error: {e}")))?
+    .map_err(panic_lock_error)
+}
@@ -4,10 +4,14 @@
 //! of long-running encryption and decryption operations.
 
 use crate::commands::types::{
-    CommandError, CommandResponse, ErrorHandler, ProgressDetails, ValidateInput, ValidationHelper,
+    ByteSize, CommandError, CommandResponse, DurationMs, ErrorCode, ErrorHandler, FormatHints,
+    IoPriority, ProgressDetails, ValidateInput,
 };
+use crate::commands::validation::{MaxLength, NonEmptyId, check_field, input_rules};
 use crate::constants::*;
 use crate::prelude::*;
+use crate::services::shared::infrastructure::io;
+use crate::services::vault::infrastructure::persistence::AppConfig;
 
 /// Input for encryption status command
 #[derive(Debug, Deserialize, specta::Type)]
@@ -29,8 +33,14 @@ pub struct GetProgressResponse {
     pub message: String,
     pub details: Option<ProgressDetails>,
     pub timestamp: chrono::DateTime<chrono::Utc>,
-    pub estimated_time_remaining: Option<u64>,
+    pub estimated_time_remaining: Option<DurationMs>,
     pub is_complete: bool,
+    /// Display form of `estimated_time_remaining`
+    pub format_hints: Option<FormatHints>,
+    /// I/O priority the operation is running with
+    pub io_priority: IoPriority,
+    /// Trace ID of the command that started the operation
+    pub trace_id: Option<String>,
 }
 
 /// Response from encryption status command
@@ -42,10 +52,12 @@ pub struct EncryptionStatusResponse {
     pub current_file: Option<String>,
     pub total_files: usize,
     pub processed_files: usize,
-    pub total_size: u64,
-    pub processed_size: u64,
-    pub estimated_time_remaining: Option<u64>, // in seconds
+    pub total_size: ByteSize,
+    pub processed_size: ByteSize,
+    pub estimated_time_remaining: Option<DurationMs>,
     pub error_message: Option<String>,
+    /// Display forms of `total_size` and `estimated_time_remaining`
+    pub format_hints: Option<FormatHints>,
 }
 
 /// Encryption operation status
@@ -58,29 +70,15 @@ pub enum EncryptionStatus {
     Cancelled,
 }
 
-impl ValidateInput for GetEncryptionStatusInput {
-    fn validate(&self) -> Result<(), Box<CommandError>> {
-        ValidationHelper::validate_not_empty(&self.operation_id, "Operation ID")?;
-        ValidationHelper::validate_length(
-            &self.operation_id,
-            "Operation ID",
-            1,
-            MAX_OPERATION_ID_LENGTH,
-        )?;
-        Ok(())
+input_rules! {
+    GetEncryptionStatusInput {
+        operation_id("Operation ID"): [NonEmptyId, MaxLength { max: MAX_OPERATION_ID_LENGTH }],
     }
 }
 
-impl ValidateInput for GetProgressInput {
-    fn validate(&self) -> Result<(), Box<CommandError>> {
-        ValidationHelper::validate_not_empty(&self.operation_id, "Operation ID")?;
-        ValidationHelper::validate_length(
-            &self.operation_id,
-            "Operation ID",
-            1,
-            MAX_OPERATION_ID_LENGTH,
-        )?;
-        Ok(())
+input_rules! {
+    GetProgressInput {
+        operation_id("Operation ID"): [NonEmptyId, MaxLength { max: MAX_OPERATION_ID_LENGTH }],
     }
 }
 
@@ -91,13 +89,8 @@ fn validate(&self) -> Result<(), Box<CommandError>> {
 pub async fn get_encryption_status(
     input: GetEncryptionStatusInput,
 ) -> CommandResponse<EncryptionStatusResponse> {
-    // Create error handler
-    let error_handler = ErrorHandler::new();
-
     // Validate input
-    input
-        .validate()
-        .map_err(|e| error_handler.handle_validation_error("input", &e.message))?;
+    input.validate()?;
 
     // Log operation start with structured fields
     info!(
@@ -115,10 +108,11 @@ pub async fn get_encryption_status(
         current_file: None,
         total_files: 1,
         processed_files: 1,
-        total_size: 1024,
-        processed_size: 1024,
+        total_size: ByteSize(1024),
+        processed_size: ByteSize(1024),
         estimated_time_remaining: None,
         error_message: None,
+        format_hints: Some(FormatHints::size(ByteSize(1024))),
     };
 
     // Log operation completion
@@ -140,9 +134,7 @@ pub async fn get_progress(input: GetProgressInput) -> CommandResponse<GetProgres
     let error_handler = ErrorHandler::new();
 
     // Validate input
-    input
-        .validate()
-        .map_err(|e| error_handler.handle_validation_error("input", &e.message))?;
+    input.validate()?;
 
     // Get progress from global tracker
     match super::get_global_progress(&input.operation_id) {
@@ -157,6 +149,9 @@ pub async fn get_progress(input: GetProgressInput) -> CommandResponse<GetProgres
                 timestamp: progress.timestamp,
                 estimated_time_remaining: progress.estimated_time_remaining,
                 is_complete,
+                format_hints: progress.estimated_time_remaining.map(FormatHints::duration),
+                io_priority: progress.io_priority,
+                trace_id: progress.trace_id,
             })
         }
         None => {
@@ -168,3 +163,60 @@ pub async fn get_progress(input: GetProgressInput) -> CommandResponse<GetProgres
         }
     }
 }
+
+/// I/O priority for an operation: the requested one, else the app default
+pub(crate) fn resolve_io_priority(requested: Option<IoPriority>) -> IoPriority {
+    requested.unwrap_or_else(|| match AppConfig::load() {
+        Ok(config) => config.default_io_priority,
+        Err(e) => {
+            warn!(error = %e, "Failed to load app config, using normal I/O priority");
+            IoPriority::Normal
+        }
+    })
+}
+
+/// Switch a running operation between normal and background I/O priority
+///
+/// Takes effect at the operation's next chunk boundary.
+#[tauri::command]
+#[specta::specta]
+#[instrument]
+pub async fn set_operation_priority(
+    operation_id: String,
+    priority: IoPriority,
+) -> CommandResponse<()> {
+    check_field("operation_id", "Operation ID", &operation_id, &NonEmptyId)?;
+
+    if !io::set_operation_priority(&operation_id, priority) {
+        return Err(Box::new(
+            CommandError::operation(
+                ErrorCode::OperationNotFound,
+                format!("Operation '{}' is not running", operation_id),
+            )
+            .with_recovery_guidance("The operation may already have finished"),
+        ));
+    }
+
+    info!(?priority, "Operation I/O priority changed");
+    Ok(())
+}
+
+/// Set the I/O priority used by operations that don't choose one
+#[tauri::command]
+#[specta::specta]
+#[instrument]
+pub async fn set_default_io_priority(priority: IoPriority) -> CommandResponse<()> {
+    let save = AppConfig::load().and_then(|mut config| {
+        config.default_io_priority = priority;
+        config.save()
+    });
+    save.map_err(|e| {
+        Box::new(
+            CommandError::operation(
+                ErrorCode::StorageFailed,
+                format!("Failed to save I/O priority: {}", e),
+            )
+            .with_recovery_guidance("Check that the config directory is writable"),
+        )
+    })
+}
@@ -0,0 +1,128 @@
+//! Recovery share decryption command
+//!
+//! Thin wrapper following Command → Manager → Service pattern.
+//! Decrypts a vault when enough Shamir share holders cooperate.
+
+use super::decryption::RenamedPath;
+use crate::commands::types::{
+    CommandError, CommandResponse, ErrorCode, ValidateInput, ValidationRule,
+};
+use crate::commands::validation::{
+    NonEmpty, NonEmptyId, PathWithinAllowedRoots, input_rules, invalid,
+};
+use crate::prelude::*;
+use crate::services::crypto::CryptoError;
+use crate::services::crypto::CryptoManager;
+use std::path::Path;
+
+/// Input for recovery share decryption
+#[derive(Debug, Deserialize, specta::Type)]
+pub struct DecryptWithRecoverySharesInput {
+    pub vault_id: String,
+    pub shares: Vec<String>,
+    pub encrypted_file: String,
+    pub output_dir: String,
+}
+
+/// Result of recovery share decryption
+#[derive(Debug, Serialize, specta::Type)]
+pub struct RecoveryDecryptionResult {
+    pub extracted_files: Vec<String>,
+    pub output_dir: String,
+    /// Entries renamed to fit platform path limits
+    pub renamed_paths: Vec<RenamedPath>,
+}
+
+fn require_share(input: &DecryptWithRecoverySharesInput) -> Result<(), Box<CommandError>> {
+    if input.shares.iter().all(|s| s.trim().is_empty()) {
+        return Err(Box::new(invalid(
+            "shares",
+            ValidationRule::NonEmptyList,
+            "At least one recovery share is required",
+        )));
+    }
+    Ok(())
+}
+
+// Blank share fields are allowed as long as one share is filled in
+input_rules! {
+    DecryptWithRecoverySharesInput {
+        vault_id("Vault ID"): [NonEmptyId],
+        encrypted_file("Encrypted file path"): [NonEmpty],
+        output_dir("Output directory"): [PathWithinAllowedRoots],
+    }
+    then require_share
+}
+
+#[tauri::command]
+#[specta::specta]
+#[instrument(skip(input), fields(vault_id = %input.vault_id, share_count = input.shares.len()))]
+pub async fn decrypt_with_recovery_shares(
+    input: DecryptWithRecoverySharesInput,
+) -> CommandResponse<RecoveryDecryptionResult> {
+    input.validate()?;
+
+    let shares: Vec<String> = input
+        .shares
+        .iter()
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+        .collect();
+
+    let manager = CryptoManager::new();
+    let result = manager
+        .decrypt_with_recovery_shares(
+            &input.vault_id,
+            &shares,
+            Path::new(&input.encrypted_file),
+            Path::new(&input.output_dir),
+        )
+        .map_err(|e| {
+            let (code, guidance) = match &e {
+                CryptoError::InvalidInput(_) => (
+                    ErrorCode::InvalidInput,
+                    "Check each share was entered exactly and that enough holders contributed",
+                ),
+                CryptoError::DecryptionFailed(_) => (
+                    ErrorCode::DecryptionFailed,
+                    "Make sure all shares belong to this vault",
+                ),
+                CryptoError::FileNotFound(_) => (
+                    ErrorCode::FileNotFound,
+                    "Select the encrypted vault file to recover",
+                ),
+                CryptoError::AppTooOld { .. } => (
+                    ErrorCode::AppVersionTooOld,
+                    "Install the latest version of Barqly Vault to open this archive",
+                ),
+                _ => (
+                    ErrorCode::DecryptionFailed,
+                    "The vault may have been encrypted before recovery shares were created",
+                ),
+            };
+            Box::new(CommandError::operation(code, e.to_string()).with_recovery_guidance(guidance))
+        })?;
+
+    info!(
+        vault_id = %input.vault_id,
+        extracted_files_count = result.files.len(),
+        "Recovery share decryption completed"
+    );
+
+    Ok(RecoveryDecryptionResult {
+        extracted_files: result
+            .files
+            .iter()
+            .map(|file_info| file_info.path.to_string_lossy().to_string())
+            .collect(),
+        output_dir: input.output_dir,
+        renamed_paths: result
+            .renamed_paths
+            .iter()
+            .map(|mapping| RenamedPath {
+                original_path: mapping.original_path.to_string_lossy().to_string(),
+                extracted_path: mapping.extracted_path.to_string_lossy().to_string(),
+            })
+            .collect(),
+    })
+}
@@ -0,0 +1,101 @@
+//! Recovery simulation command
+//!
+//! Thin wrapper following Command → Manager → Service pattern.
+//! Rehearses a restore from a recovery kit on a pristine, sandboxed profile,
+//! so gaps in the kit show up now instead of when an executor needs it.
+
+use crate::commands::types::{CommandError, CommandResponse, ErrorCode, ValidateInput};
+use crate::commands::validation::{ExistingFile, NonEmpty, input_rules};
+use crate::logging::spawn_blocking;
+use crate::prelude::*;
+use crate::services::crypto::application::services::{
+    RecoveryCredentials, RecoverySimulationReport,
+};
+use crate::services::crypto::{CryptoError, CryptoManager};
+use age::secrecy::SecretString;
+use std::path::Path;
+
+/// Credentials to test-decrypt the archive with
+#[derive(Deserialize, specta::Type)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SimulationCredentialsInput {
+    /// Passphrase of a key file in the kit
+    Passphrase { passphrase: String },
+    /// PIN of an attached YubiKey
+    #[serde(rename = "yubikey")]
+    YubiKey { pin: String },
+}
+
+impl From<SimulationCredentialsInput> for RecoveryCredentials {
+    fn from(input: SimulationCredentialsInput) -> Self {
+        match input {
+            SimulationCredentialsInput::Passphrase { passphrase } => {
+                Self::Passphrase(SecretString::from(passphrase))
+            }
+            SimulationCredentialsInput::YubiKey { pin } => Self::YubiKey {
+                pin: SecretString::from(pin),
+            },
+        }
+    }
+}
+
+/// Input for simulating a recovery
+#[derive(Deserialize, specta::Type)]
+pub struct SimulateRecoveryInput {
+    /// The recovery kit folder, or any file in it
+    pub recovery_kit_path: String,
+    /// Encrypted archive the kit is meant to recover
+    pub archive_path: String,
+    /// Without credentials the archive is analyzed but not decrypted
+    pub credentials: Option<SimulationCredentialsInput>,
+}
+
+input_rules! {
+    SimulateRecoveryInput {
+        recovery_kit_path("Recovery kit"): [NonEmpty],
+        archive_path("Archive"): [ExistingFile],
+    }
+}
+
+/// Rehearse restoring an archive using only its recovery kit
+///
+/// The kit is imported into a temporary profile that is deleted afterwards;
+/// the live profile is never written. Each step reports whether what it
+/// needed came from the kit or would have had to come from this machine.
+#[tauri::command]
+#[specta::specta]
+#[instrument(skip(input), fields(with_credentials = input.credentials.is_some()))]
+pub async fn simulate_recovery(
+    input: SimulateRecoveryInput,
+) -> CommandResponse<RecoverySimulationReport> {
+    input.validate()?;
+
+    let SimulateRecoveryInput {
+        recovery_kit_path,
+        archive_path,
+        credentials,
+    } = input;
+
+    spawn_blocking(move || {
+        CryptoManager::new().simulate_recovery(
+            Path::new(&recovery_kit_path),
+            Path::new(&archive_path),
+            credentials.map(RecoveryCredentials::from),
+        )
+    })
+    .await
+    .map_err(|e| CommandError::operation(ErrorCode::InternalError, format!("Task error: {e}")))?
+    .map_err(|e| {
+        let (code, guidance) = match &e {
+            CryptoError::FileNotFound(_) => (
+                ErrorCode::FileNotFound,
+                "Select the recovery kit folder and the archive it belongs to",
+            ),
+            _ => (
+                ErrorCode::StorageFailed,
+                "Check there is free space in the temporary folder and try again",
+            ),
+        };
+        Box::new(CommandError::operation(code, e.to_string()).with_recovery_guidance(guidance))
+    })
+}
@@ -0,0 +1,144 @@
+//! Damaged archive salvage commands
+//!
+//! Thin wrappers following Command → Manager → Service pattern.
+//! When an archive fails authentication (e.g. a bad sector), these recover
+//! every file that survives instead of giving up on the whole archive.
+
+use crate::commands::types::{
+    CommandError, CommandResponse, ErrorCode, ValidateInput, ValidationRule,
+};
+use crate::commands::validation::{
+    ExistingFile, NonEmpty, NonEmptyId, PathWithinAllowedRoots, input_rules, invalid,
+};
+use crate::prelude::*;
+use crate::services::crypto::application::services::SalvageReport;
+use crate::services::crypto::{CryptoError, CryptoManager};
+use age::secrecy::SecretString;
+use std::path::Path;
+
+/// Input for predicting what a salvage would recover
+#[derive(Debug, Deserialize, specta::Type)]
+pub struct AssessSalvageInput {
+    pub encrypted_file: String,
+    pub key_id: String,
+    pub passphrase: String,
+}
+
+/// Input for salvaging a damaged archive
+#[derive(Debug, Deserialize, specta::Type)]
+pub struct SalvageDecryptInput {
+    pub encrypted_file: String,
+    pub key_id: String,
+    pub passphrase: String,
+    pub output_dir: String,
+    /// Must be true: the user accepts that restored files may be incomplete
+    #[serde(default)]
+    pub accept_partial: bool,
+}
+
+input_rules! {
+    AssessSalvageInput {
+        encrypted_file("Encrypted file path"): [NonEmpty],
+        key_id("Key ID"): [NonEmptyId],
+        passphrase("Passphrase"): [NonEmpty],
+        encrypted_file("Encrypted file"): [ExistingFile],
+    }
+}
+
+fn require_accept_partial(input: &SalvageDecryptInput) -> Result<(), Box<CommandError>> {
+    if !input.accept_partial {
+        return Err(Box::new(invalid(
+            "accept_partial",
+            ValidationRule::Format,
+            "Salvage may restore incomplete files; set accept_partial to confirm",
+        )));
+    }
+    Ok(())
+}
+
+input_rules! {
+    SalvageDecryptInput {
+        encrypted_file("Encrypted file path"): [NonEmpty],
+        key_id("Key ID"): [NonEmptyId],
+        passphrase("Passphrase"): [NonEmpty],
+        output_dir("Output directory"): [PathWithinAllowedRoots],
+        encrypted_file("Encrypted file"): [ExistingFile],
+    }
+    then require_accept_partial
+}
+
+/// Predict which files a salvage of a damaged archive would recover
+///
+/// Decrypts in memory and writes nothing.
+#[tauri::command]
+#[specta::specta]
+#[instrument(skip(input), fields(key_id = %input.key_id))]
+pub async fn assess_salvage(input: AssessSalvageInput) -> CommandResponse<SalvageReport> {
+    input.validate()?;
+
+    let manager = CryptoManager::new();
+    manager
+        .salvage_archive(
+            &input.encrypted_file,
+            &input.key_id,
+            SecretString::from(input.passphrase),
+            None,
+        )
+        .map_err(salvage_error)
+}
+
+/// Restore every file that survives in a damaged archive
+///
+/// Requires `accept_partial`. Truncated files are written as
+/// `<name>.partial`; the report lists recovered, partial and lost files.
+#[tauri::command]
+#[specta::specta]
+#[instrument(skip(input), fields(key_id = %input.key_id))]
+pub async fn salvage_decrypt(input: SalvageDecryptInput) -> CommandResponse<SalvageReport> {
+    input.validate()?;
+
+    let manager = CryptoManager::new();
+    let report = manager
+        .salvage_archive(
+            &input.encrypted_file,
+            &input.key_id,
+            SecretString::from(input.passphrase),
+            Some(Path::new(&input.output_dir)),
+        )
+        .map_err(salvage_error)?;
+
+    info!(
+        recovered = report.recovered.len(),
+        partial = report.partial.len(),
+        lost = report.lost.len(),
+        "Salvage decryption completed"
+    );
+    Ok(report)
+}
+
+fn salvage_error(e: CryptoError) -> Box<CommandError> {
+    error!(error = %e, "Archive salvage failed");
+    let (code, guidance) = match &e {
+        CryptoError::InvalidInput(_) => (
+            ErrorCode::InvalidInput,
+            "Salvage works with passphrase keys; select the archive's passphrase key",
+        ),
+        CryptoError::KeyNotARecipient { .. } => (
+            ErrorCode::DecryptionFailed,
+            "Select a key this archive was encrypted to",
+        ),
+        CryptoError::KeyMediaNotPresent { .. } => (
+            ErrorCode::KeyMediaNotPresent,
+            "Insert the drive holding this key file, then try again",
+        ),
+        CryptoError::IoError(_) => (
+            ErrorCode::StorageFailed,
+            "Check that the output directory is writable",
+        ),
+        _ => (
+            ErrorCode::DecryptionFailed,
+            "If the archive header is damaged nothing can be salvaged; try another copy",
+        ),
+    };
+    Box::new(CommandError::operation(code, e.to_string()).with_recovery_guidance(guidance))
+}
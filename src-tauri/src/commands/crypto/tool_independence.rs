@@ -0,0 +1,100 @@
+//! Tool independence check command
+//!
+//! Thin wrapper following Command → Manager → Service pattern.
+//! Proves an archive is a plain age-encrypted tarball that standard tools can
+//! recover, and writes the recovery steps into the vault's recovery kit.
+
+use crate::commands::types::{CommandError, CommandResponse, ErrorCode, ValidateInput};
+use crate::commands::validation::{ExistingVaultId, NonEmpty, NonEmptyId, input_rules};
+use crate::logging::spawn_blocking;
+use crate::prelude::*;
+use crate::services::crypto::application::services::ToolIndependenceReport;
+use crate::services::crypto::{CryptoError, CryptoManager};
+use age::secrecy::SecretString;
+
+/// Input for checking an archive against standard tools
+#[derive(Debug, Deserialize, specta::Type)]
+pub struct VerifyToolIndependenceInput {
+    pub vault_id: String,
+    /// Archive to check (see `list_archives`); must be its newest revision
+    pub archive_id: String,
+    /// A passphrase key of the vault
+    pub key_id: String,
+    pub passphrase: String,
+}
+
+input_rules! {
+    VerifyToolIndependenceInput {
+        vault_id("Vault ID"): [ExistingVaultId],
+        archive_id("Archive ID"): [NonEmptyId],
+        key_id("Key ID"): [NonEmptyId],
+        passphrase("Passphrase"): [NonEmpty],
+    }
+}
+
+/// Check an archive decrypts and unpacks with standard age and tar
+///
+/// Decrypts in memory with the key's raw identity and reads the payload with
+/// a generic tar reader; nothing is extracted. A failed check is reported in
+/// the result. When the archive passes, the returned transcript is also
+/// written into the recovery kit beside it.
+#[tauri::command]
+#[specta::specta]
+#[instrument(skip(input), fields(vault_id = %input.vault_id, archive_id = %input.archive_id))]
+pub async fn verify_tool_independence(
+    input: VerifyToolIndependenceInput,
+) -> CommandResponse<ToolIndependenceReport> {
+    input.validate()?;
+
+    let VerifyToolIndependenceInput {
+        vault_id,
+        archive_id,
+        key_id,
+        passphrase,
+    } = input;
+
+    let report = spawn_blocking(move || {
+        CryptoManager::new().verify_tool_independence(
+            &vault_id,
+            &archive_id,
+            &key_id,
+            SecretString::from(passphrase),
+        )
+    })
+    .await
+    .map_err(|e| CommandError::operation(ErrorCode::InternalError, format!("Task error: {e}")))?
+    .map_err(|e| {
+        let (code, guidance) = match &e {
+            CryptoError::KeyMediaNotPresent { .. } => (
+                ErrorCode::KeyMediaNotPresent,
+                "Insert the drive holding this key file, then try again",
+            ),
+            CryptoError::FileNotFound(_) => (
+                ErrorCode::FileNotFound,
+                "Only the newest encryption of an archive can be checked",
+            ),
+            CryptoError::InvalidKey(_) => (
+                ErrorCode::KeyNotFound,
+                "Select a passphrase key registered with this vault",
+            ),
+            CryptoError::InvalidInput(_) => (
+                ErrorCode::InvalidInput,
+                "Select a passphrase key and an archive of this vault",
+            ),
+            _ => (
+                ErrorCode::DecryptionFailed,
+                "Check your passphrase and try again",
+            ),
+        };
+        Box::new(CommandError::operation(code, e.to_string()).with_recovery_guidance(guidance))
+    })?;
+
+    info!(
+        independent = report.is_independent(),
+        requires_documented_formats = report.requires_documented_formats(),
+        recovery_kit_updated = report.recovery_kit.is_some(),
+        "Checked archive against standard tools"
+    );
+
+    Ok(report)
+}
@@ -2,13 +2,35 @@
 //!
 //! Analyzes encrypted .age files to extract metadata needed for decryption UI.
 //! Handles vault name extraction, desanitization, manifest detection, and key discovery.
-
-use crate::commands::types::{CommandError, CommandResponse, ErrorCode, ValidationHelper};
+//! When a key is supplied, the manifest embedded in the archive is read and
+//! preferred over the external manifest on this machine, and small in-memory
+//! previews of the archive's files can be requested alongside it. For an
+//! archive in an output folder shared by several vaults, the vault the
+//! cross-vault output index says wrote it is reported too.
+
+use crate::commands::types::{CommandError, CommandResponse, ErrorCode, ValidateInput};
+use crate::commands::validation::{ExistingFile, NonEmpty, input_rules};
+use crate::features::{FeatureFlag, require_feature};
+use crate::logging::current_trace_id;
 use crate::prelude::*;
+use crate::services::crypto::application::services::{EmbeddedManifestService, check_app_version};
+use crate::services::crypto::infrastructure::{ArchivePreamble, read_archive_preamble};
+use crate::services::crypto::{CryptoError, CryptoManager, ManifestSource};
+use crate::services::file::infrastructure::file_operations::{
+    EntryPreview, PreviewLimits, top_level_type,
+};
 use crate::services::key_management::shared::domain::models::VaultKey;
 use crate::services::shared::infrastructure::label_sanitization::desanitize_vault_name;
 use crate::services::vault::VaultManager;
+use crate::services::vault::application::services::{ManifestDiscrepancy, OutputNamingService};
+use crate::services::vault::infrastructure::persistence::ManifestSignatureCheck;
+use crate::services::vault::infrastructure::persistence::metadata::{
+    RecipientAttribution, VaultMetadata,
+};
+use age::secrecy::SecretString;
+use chrono::{DateTime, Utc};
 use regex::Regex;
+use std::collections::BTreeMap;
 use std::path::Path;
 
 /// Request to analyze an encrypted vault file
@@ -16,6 +38,20 @@
 pub struct AnalyzeEncryptedVaultRequest {
     /// Absolute path to the encrypted .age file
     pub encrypted_file_path: String,
+    /// Key to read the embedded manifest with (optional)
+    pub key_id: Option<String>,
+    /// Passphrase or PIN for `key_id`
+    pub passphrase: Option<String>,
+    /// Also preview the archive's files (needs `key_id` and `passphrase`)
+    #[serde(default)]
+    pub preview: bool,
+}
+
+input_rules! {
+    AnalyzeEncryptedVaultRequest {
+        encrypted_file_path("Encrypted file path"): [NonEmpty],
+        encrypted_file_path("Encrypted file"): [ExistingFile],
+    }
 }
 
 /// Response containing vault analysis results
@@ -36,6 +72,9 @@ pub struct AnalyzeEncryptedVaultResponse {
     // Key information
     /// Associated keys from manifest (empty if recovery mode)
     pub associated_keys: Vec<VaultKey>,
+    /// Vault keys and contacts the archive is encrypted to, from the manifest
+    /// (e.g. "2 vault keys + contact 'Sam'"); null without one
+    pub encrypted_to: Option<RecipientAttribution>,
 
     // Metadata from filename
     /// Creation date extracted from filename (e.g., "2025-01-13")
@@ -44,6 +83,51 @@ pub struct AnalyzeEncryptedVaultResponse {
     // Recovery mode indicators
     /// True if manifest is missing (disaster recovery scenario)
     pub is_recovery_mode: bool,
+
+    // Embedded manifest reconciliation
+    /// Manifest the analysis is based on: embedded copy, external sidecar, or none
+    pub manifest_source: ManifestSource,
+    /// Fields where the external manifest disagrees with the embedded one
+    pub manifest_discrepancies: Vec<ManifestDiscrepancy>,
+    /// Signature check of the external manifest, when there is one
+    pub manifest_signature: Option<ManifestSignatureCheck>,
+
+    // Armored archive preamble
+    /// Readable notes at the top of an armored archive (app, date, recovery
+    /// instructions), available without a key
+    pub archive_preamble: Option<ArchivePreamble>,
+
+    // Content previews
+    /// Previews of the archive's files, when requested with a key
+    pub previews: Option<Vec<EntryPreview>>,
+
+    // Content breakdown
+    /// Files and bytes per top-level content type, from the manifest
+    pub content_summary: Vec<ContentTypeSummary>,
+
+    // Shared output folder
+    /// Vault that wrote this archive, when it is in an output folder the
+    /// app has written to; helps sort out folders mixing several vaults
+    pub output_folder_claim: Option<OutputFolderClaim>,
+}
+
+/// The vault that wrote an archive in an output folder
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, specta::Type)]
+pub struct OutputFolderClaim {
+    pub vault_id: String,
+    pub vault_name: String,
+    pub recorded_at: DateTime<Utc>,
+}
+
+/// Files and bytes of one top-level content type
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, specta::Type)]
+pub struct ContentTypeSummary {
+    /// e.g. "image"; "unknown" for files with no recorded type
+    pub top_level_type: String,
+    pub file_count: usize,
+    pub total_bytes: u64,
+    /// Files whose extension doesn't match their content
+    pub mismatched_extensions: usize,
 }
 
 /// Analyze encrypted vault file and return metadata for UI display
@@ -64,47 +148,12 @@ pub async fn analyze_encrypted_vault(
         "Analyzing encrypted vault file"
     );
 
-    // Validate input
-    ValidationHelper::validate_not_empty(&input.encrypted_file_path, "Encrypted file path")
-        .map_err(|e| {
-            Box::new(CommandError {
-                code: ErrorCode::InvalidInput,
-                message: e.message.clone(),
-                details: None,
-                recovery_guidance: Some("Provide a valid file path".to_string()),
-                user_actionable: true,
-                trace_id: None,
-                span_id: None,
-            })
-        })?;
-
-    ValidationHelper::validate_path_exists(&input.encrypted_file_path, "Encrypted file").map_err(
-        |e| {
-            Box::new(CommandError {
-                code: ErrorCode::FileNotFound,
-                message: e.message.clone(),
-                details: None,
-                recovery_guidance: Some("Check that the file exists".to_string()),
-                user_actionable: true,
-                trace_id: None,
-                span_id: None,
-            })
-        },
-    )?;
+    if input.preview {
+        require_feature(FeatureFlag::ArchivePreviews)?;
+    }
 
-    ValidationHelper::validate_is_file(&input.encrypted_file_path, "Encrypted file").map_err(
-        |e| {
-            Box::new(CommandError {
-                code: ErrorCode::InvalidInput,
-                message: e.message.clone(),
-                details: None,
-                recovery_guidance: Some("Path must point to a file, not a directory".to_string()),
-                user_actionable: true,
-                trace_id: None,
-                span_id: None,
-            })
-        },
-    )?;
+    // Validate input
+    input.validate()?;
 
     // Extract filename from path
     let file_path = Path::new(&input.encrypted_file_path);
@@ -118,13 +167,23 @@ pub async fn analyze_encrypted_vault(
                 details: None,
                 recovery_guidance: Some("Ensure the path contains a valid filename".to_string()),
                 user_actionable: true,
-                trace_id: None,
+                trace_id: current_trace_id(),
                 span_id: None,
+                validation: None,
+                help_available: true,
             })
         })?;
 
     debug!(filename = %filename, "Extracted filename from path");
 
+    // Armored archives say what made them in their first lines; binary ones don't
+    let archive_preamble = read_archive_preamble(file_path).unwrap_or_else(|e| {
+        warn!(error = %e, "Could not read archive preamble");
+        None
+    });
+
+    let output_folder_claim = output_folder_claim(file_path);
+
     // Parse vault name and date from filename
     // Expected format: "Sam-Family-Vault-2025-01-13.age" or "Sam-Family-Vault.age"
     // Also detects "-shared" suffix for shared bundles
@@ -161,48 +220,105 @@ pub async fn analyze_encrypted_vault(
             manifest_exists: false,
             vault_id: None,
             associated_keys: vec![], // Will use global key list in UI
+            encrypted_to: None,
             creation_date,
             is_recovery_mode: false, // NOT recovery mode - normal decryption flow
+            manifest_source: ManifestSource::None,
+            manifest_discrepancies: vec![],
+            manifest_signature: None,
+            archive_preamble,
+            previews: None,
+            content_summary: vec![],
+            output_folder_claim,
         });
     }
 
+    // With a key, read the manifest embedded in the archive and prefer it
+    if let (Some(key_id), Some(passphrase)) = (&input.key_id, &input.passphrase) {
+        let crypto_manager = CryptoManager::new();
+        let passphrase = SecretString::from(passphrase.clone());
+        let result = if input.preview {
+            crypto_manager
+                .read_archive_previews(
+                    &input.encrypted_file_path,
+                    key_id,
+                    passphrase,
+                    &PreviewLimits::default(),
+                )
+                .map(|(resolution, previews)| (resolution, Some(previews)))
+        } else {
+            crypto_manager
+                .read_archive_manifest(&input.encrypted_file_path, key_id, passphrase)
+                .map(|resolution| (resolution, None))
+        };
+        let (resolution, previews) = result.map_err(|e| match e {
+            CryptoError::AppTooOld { .. } => app_too_old_error(&e),
+            CryptoError::KeyMediaNotPresent { .. } => Box::new(CommandError::operation(
+                ErrorCode::KeyMediaNotPresent,
+                e.to_string(),
+            )),
+            _ => Box::new(
+                CommandError::operation(
+                    ErrorCode::DecryptionFailed,
+                    format!("Failed to read embedded manifest: {}", e),
+                )
+                .with_recovery_guidance("Check the selected key and passphrase"),
+            ),
+        })?;
+
+        let preferred = resolution.preferred();
+        let response = AnalyzeEncryptedVaultResponse {
+            vault_name: preferred
+                .map(|m| m.label().to_string())
+                .unwrap_or(vault_name),
+            vault_name_sanitized: preferred
+                .map(|m| m.vault.sanitized_name.clone())
+                .unwrap_or(vault_name_sanitized),
+            manifest_exists: resolution.external.is_some(),
+            vault_id: preferred.map(|m| m.vault_id().to_string()),
+            associated_keys: preferred.map(vault_keys_from_manifest).unwrap_or_default(),
+            encrypted_to: preferred.map(VaultMetadata::recipient_attribution),
+            creation_date,
+            is_recovery_mode: resolution.external.is_none(),
+            manifest_source: resolution.source,
+            content_summary: preferred.map(summarize_content_types).unwrap_or_default(),
+            manifest_discrepancies: resolution.discrepancies,
+            manifest_signature: resolution.signature,
+            archive_preamble,
+            previews,
+            output_folder_claim,
+        };
+
+        info!(
+            manifest_source = ?response.manifest_source,
+            discrepancy_count = response.manifest_discrepancies.len(),
+            key_count = response.associated_keys.len(),
+            preview_count = response.previews.as_ref().map_or(0, Vec::len),
+            "Vault analysis complete using embedded manifest"
+        );
+
+        return Ok(response);
+    }
+
     // Check if manifest exists and get vault info
     let vault_manager = VaultManager::new();
     let manifest_result = vault_manager
         .get_vault_by_sanitized_name(&vault_name_sanitized)
         .await;
 
-    let (manifest_exists, vault_id, associated_keys, is_recovery_mode) = match manifest_result {
+    let (
+        manifest_exists,
+        vault_id,
+        associated_keys,
+        encrypted_to,
+        is_recovery_mode,
+        content_summary,
+    ) = match manifest_result {
         Ok(Some(vault_metadata)) => {
             // Manifest found - normal mode
+            check_app_version(&vault_metadata).map_err(|e| app_too_old_error(&e))?;
             let vault_id = vault_metadata.vault_id().to_string();
-            let keys: Vec<VaultKey> = vault_metadata
-                .recipients()
-                .iter()
-                .map(|recipient| VaultKey {
-                    id: recipient.key_id.clone(),
-                    label: recipient.label.clone(),
-                    key_type: match &recipient.recipient_type {
-                        crate::services::vault::infrastructure::persistence::metadata::RecipientType::Passphrase { key_filename } => {
-                            crate::services::key_management::shared::domain::models::KeyType::Passphrase {
-                                key_id: key_filename.clone(),
-                            }
-                        }
-                        crate::services::vault::infrastructure::persistence::metadata::RecipientType::YubiKey { serial, firmware_version, .. } => {
-                            crate::services::key_management::shared::domain::models::KeyType::YubiKey {
-                                serial: serial.clone(),
-                                firmware_version: firmware_version.clone(),
-                            }
-                        }
-                        crate::services::vault::infrastructure::persistence::metadata::RecipientType::PublicKeyOnly => {
-                            crate::services::key_management::shared::domain::models::KeyType::Recipient
-                        }
-                    },
-                    lifecycle_status: crate::services::key_management::shared::domain::models::key_lifecycle::KeyLifecycleStatus::Active,
-                    created_at: recipient.created_at,
-                    last_used: None,
-                })
-                .collect();
+            let keys = vault_keys_from_manifest(&vault_metadata);
 
             info!(
                 vault_id = %vault_id,
@@ -210,7 +326,16 @@ pub async fn analyze_encrypted_vault(
                 "Found vault manifest with associated keys"
             );
 
-            (true, Some(vault_id), keys, false)
+            let summary = summarize_content_types(&vault_metadata);
+            let encrypted_to = vault_metadata.recipient_attribution();
+            (
+                true,
+                Some(vault_id),
+                keys,
+                Some(encrypted_to),
+                false,
+                summary,
+            )
         }
         Ok(None) | Err(_) => {
             // Manifest not found - recovery mode
@@ -219,18 +344,36 @@ pub async fn analyze_encrypted_vault(
                 "Vault manifest not found - entering recovery mode"
             );
 
-            (false, None, vec![], true)
+            (false, None, vec![], None, true, vec![])
         }
     };
 
+    let manifest_signature = if manifest_exists {
+        EmbeddedManifestService::new().check_external_signature(&vault_name_sanitized)
+    } else {
+        None
+    };
+
     let response = AnalyzeEncryptedVaultResponse {
         vault_name,
         vault_name_sanitized,
         manifest_exists,
         vault_id,
         associated_keys,
+        encrypted_to,
         creation_date,
         is_recovery_mode,
+        manifest_source: if manifest_exists {
+            ManifestSource::External
+        } else {
+            ManifestSource::None
+        },
+        manifest_discrepancies: vec![],
+        manifest_signature,
+        archive_preamble,
+        previews: None,
+        content_summary,
+        output_folder_claim,
     };
 
     info!(
@@ -243,6 +386,83 @@ pub async fn analyze_encrypted_vault(
     Ok(response)
 }
 
+/// The output index's claim on the archive, if it has one
+fn output_folder_claim(file_path: &Path) -> Option<OutputFolderClaim> {
+    match OutputNamingService::new().claim_for(file_path) {
+        Ok(claim) => claim.map(|claim| OutputFolderClaim {
+            vault_id: claim.vault_id,
+            vault_name: claim.vault_name,
+            recorded_at: claim.recorded_at,
+        }),
+        Err(e) => {
+            warn!(error = %e, "Could not check the output index");
+            None
+        }
+    }
+}
+
+/// Error for an archive whose manifest needs a newer app
+fn app_too_old_error(error: &CryptoError) -> Box<CommandError> {
+    Box::new(CommandError::operation(
+        ErrorCode::AppVersionTooOld,
+        error.to_string(),
+    ))
+}
+
+/// Group manifest files by top-level content type
+fn summarize_content_types(vault_metadata: &VaultMetadata) -> Vec<ContentTypeSummary> {
+    let mut groups: BTreeMap<&str, ContentTypeSummary> = BTreeMap::new();
+    for file in &vault_metadata.content.files {
+        let top_level = file
+            .content_type
+            .as_deref()
+            .map_or("unknown", top_level_type);
+        let group = groups
+            .entry(top_level)
+            .or_insert_with(|| ContentTypeSummary {
+                top_level_type: top_level.to_string(),
+                file_count: 0,
+                total_bytes: 0,
+                mismatched_extensions: 0,
+            });
+        group.file_count += 1;
+        group.total_bytes += file.size;
+        group.mismatched_extensions += usize::from(file.content_type_mismatch);
+    }
+    groups.into_values().collect()
+}
+
+/// Map manifest recipients to the keys shown in the decrypt dropdown
+fn vault_keys_from_manifest(vault_metadata: &VaultMetadata) -> Vec<VaultKey> {
+    vault_metadata
+        .recipients()
+        .iter()
+        .map(|recipient| VaultKey {
+            id: recipient.key_id.clone(),
+            label: recipient.label.clone(),
+            key_type: match &recipient.recipient_type {
+                crate::services::vault::infrastructure::persistence::metadata::RecipientType::Passphrase { key_filename } => {
+                    crate::services::key_management::shared::domain::models::KeyType::Passphrase {
+                        key_id: key_filename.clone(),
+                    }
+                }
+                crate::services::vault::infrastructure::persistence::metadata::RecipientType::YubiKey { serial, firmware_version, .. } => {
+                    crate::services::key_management::shared::domain::models::KeyType::YubiKey {
+                        serial: serial.clone(),
+                        firmware_version: firmware_version.clone(),
+                    }
+                }
+                crate::services::vault::infrastructure::persistence::metadata::RecipientType::PublicKeyOnly => {
+                    crate::services::key_management::shared::domain::models::KeyType::Recipient
+                }
+            },
+            lifecycle_status: crate::services::key_management::shared::domain::models::key_lifecycle::KeyLifecycleStatus::Active,
+            created_at: recipient.created_at,
+            last_used: None,
+        })
+        .collect()
+}
+
 /// Parse vault filename to extract sanitized name, optional date, and shared bundle flag
 ///
 /// Expected formats:
@@ -272,8 +492,10 @@ fn parse_vault_filename(
             details: None,
             recovery_guidance: None,
             user_actionable: false,
-            trace_id: None,
+            trace_id: current_trace_id(),
             span_id: None,
+            validation: None,
+            help_available: true,
         })
     })?;
 
@@ -291,8 +513,10 @@ fn parse_vault_filename(
                 "Ensure the encrypted file follows the standard naming convention".to_string(),
             ),
             user_actionable: true,
-            trace_id: None,
+            trace_id: current_trace_id(),
             span_id: None,
+            validation: None,
+            help_available: true,
         })
     })?;
 
@@ -306,8 +530,10 @@ fn parse_vault_filename(
                 details: None,
                 recovery_guidance: None,
                 user_actionable: false,
-                trace_id: None,
+                trace_id: current_trace_id(),
                 span_id: None,
+                validation: None,
+                help_available: true,
             })
         })?;
 
@@ -390,4 +616,106 @@ fn test_parse_filename_invalid_date() {
         assert_eq!(result.1, None); // No date captured
         assert!(!result.2, "Should not be a shared bundle");
     }
+
+    #[test]
+    fn test_content_summary_groups_by_top_level_type() {
+        use crate::services::file::infrastructure::file_operations::{HashAlgorithm, RawPath};
+        use crate::services::shared::infrastructure::DeviceInfo;
+        use crate::services::vault::infrastructure::persistence::metadata::VaultFileEntry;
+
+        let file =
+            |path: &str, size: u64, content_type: Option<&str>, mismatch: bool| VaultFileEntry {
+                path: path.to_string(),
+                raw_path: RawPath::from_display(path),
+                lossy_name: false,
+                size,
+                sha256: "abc".to_string(),
+                hash_algorithm: HashAlgorithm::Sha256,
+                digest: None,
+                ownership: None,
+                content_type: content_type.map(str::to_string),
+                content_type_mismatch: mismatch,
+                transform: None,
+            };
+        let device_info = DeviceInfo {
+            machine_id: "machine-1".to_string(),
+            machine_label: "laptop".to_string(),
+            created_at: chrono::Utc::now(),
+            app_version: "2.0.0".to_string(),
+        };
+        let manifest = VaultMetadata::new(
+            "vault-001".to_string(),
+            "Estate".to_string(),
+            None,
+            "Estate".to_string(),
+            &device_info,
+            None,
+            vec![],
+            vec![
+                file("a.png", 100, Some("image/png"), false),
+                file("b.txt", 50, Some("image/jpeg"), true),
+                file("c.pdf", 10, Some("application/pdf"), false),
+                file("legacy.bin", 5, None, false),
+            ],
+            4,
+            165,
+        );
+
+        let summary = summarize_content_types(&manifest);
+
+        let types: Vec<_> = summary.iter().map(|s| s.top_level_type.as_str()).collect();
+        assert_eq!(types, vec!["application", "image", "unknown"]);
+        assert_eq!(summary[1].file_count, 2);
+        assert_eq!(summary[1].total_bytes, 150);
+        assert_eq!(summary[1].mismatched_extensions, 1);
+    }
+
+    #[test]
+    fn test_contacts_are_attributed_but_not_offered_for_decryption() {
+        use crate::services::shared::infrastructure::DeviceInfo;
+        use crate::services::vault::infrastructure::persistence::metadata::{
+            ContactRecipientInfo, RecipientInfo,
+        };
+
+        let device_info = DeviceInfo {
+            machine_id: "machine-1".to_string(),
+            machine_label: "laptop".to_string(),
+            created_at: chrono::Utc::now(),
+            app_version: "2.0.0".to_string(),
+        };
+        let key = |id: &str| {
+            RecipientInfo::new_passphrase(
+                id.to_string(),
+                format!("age1{id}"),
+                id.to_string(),
+                format!("{id}.agekey.enc"),
+            )
+        };
+        let mut manifest = VaultMetadata::new(
+            "vault-001".to_string(),
+            "Estate".to_string(),
+            None,
+            "Estate".to_string(),
+            &device_info,
+            None,
+            vec![key("home"), key("office")],
+            vec![],
+            0,
+            0,
+        );
+        manifest.encryption.contacts.push(ContactRecipientInfo {
+            contact_id: "contact-sam-1".to_string(),
+            name: "Sam".to_string(),
+            public_key: "age1sam".to_string(),
+            fingerprint: "AAAA-BBBB-CCCC-DDDD".to_string(),
+        });
+
+        let keys = vault_keys_from_manifest(&manifest);
+        assert_eq!(keys.len(), 2);
+        assert!(keys.iter().all(|key| !key.is_contact()));
+        assert_eq!(
+            manifest.recipient_attribution().summary,
+            "2 vault keys + contact 'Sam'"
+        );
+    }
 }
@@ -0,0 +1,307 @@
+//! Diagnostics commands
+//!
+//! Reports conditions that quietly degrade the app, starting with an
+//! implausible system clock (common on air-gapped machines with a dead RTC
+//! battery), lists which feature flags this build has switched on, looks up
+//! offline troubleshooting help for error codes, and pulls one command's log
+//! lines out of the app log by its trace ID.
+//!
+//! Also takes the interface's `frontend_ready` handshake. A launch whose
+//! interface doesn't make it within the startup window is recorded, with the
+//! page loads and window events seen meanwhile, for export on the next start.
+
+use crate::commands::validation::{
+    ExistingDirectory, MaxLength, NonEmpty, NonEmptyId, input_rules, invalid,
+};
+use crate::features::{FeatureFlagState, FeatureFlags};
+use crate::logging::{BUILD_TIMESTAMP, TraceId, TraceLogLines, VERSION, read_trace_lines};
+use crate::prelude::*;
+use crate::services::key_management::yubikey::domain::errors::ErrorCategory;
+use crate::services::shared::infrastructure::{ClockService, ClockStatus};
+use crate::services::vault::VaultManager;
+use crate::services::vault::application::services::LaunchWatchdog;
+use crate::services::vault::domain::VaultError;
+use crate::services::vault::domain::models::{
+    BindingsDrift, FrontendHandshake, NavigationEvent, NavigationEventKind,
+};
+use crate::types::{
+    ErrorCode, ErrorHelp, ErrorHelpContext, ValidateInput, ValidationRule, error_help,
+};
+
+/// Log lines returned when the caller doesn't set a limit
+const DEFAULT_TRACE_LINES: u32 = 500;
+
+/// Longest UI version or bindings hash accepted in a handshake
+const MAX_HANDSHAKE_FIELD_LENGTH: usize = 128;
+
+/// App build details and anything currently degraded
+#[derive(Debug, Serialize, specta::Type)]
+pub struct AppDiagnostics {
+    pub app_version: String,
+    pub build_timestamp: String,
+    pub clock: ClockStatus,
+    /// User-facing warnings, empty when nothing is degraded
+    pub warnings: Vec<String>,
+}
+
+/// Input for looking up help for an error
+#[derive(Debug, Deserialize, specta::Type)]
+pub struct GetErrorHelpInput {
+    pub code: ErrorCode,
+    /// YubiKey failure category, for more specific help
+    #[serde(default)]
+    pub yubikey_category: Option<ErrorCategory>,
+    /// Details the error carried, such as the failing path
+    #[serde(default)]
+    pub context: ErrorHelpContext,
+    /// e.g. "en-GB"; English when not given or not available
+    #[serde(default)]
+    pub locale: Option<String>,
+}
+
+/// Report build details, clock plausibility and active warnings
+#[tauri::command]
+#[specta::specta]
+#[instrument]
+pub async fn get_diagnostics() -> CommandResponse<AppDiagnostics> {
+    let clock = ClockService::global().status();
+    let warnings = clock.warning.iter().cloned().collect();
+
+    debug!(reliability = ?clock.reliability, "Collected diagnostics");
+    Ok(AppDiagnostics {
+        app_version: VERSION.to_string(),
+        build_timestamp: BUILD_TIMESTAMP.to_string(),
+        clock,
+        warnings,
+    })
+}
+
+/// Feature flags for this build and whether each is switched on
+///
+/// The UI should check these rather than the app version before offering
+/// experimental or partially-shipped features.
+#[tauri::command]
+#[specta::specta]
+#[instrument]
+pub async fn get_feature_flags() -> CommandResponse<Vec<FeatureFlagState>> {
+    Ok(FeatureFlags::current().states().to_vec())
+}
+
+/// Troubleshooting help for an error: likely causes, steps to fix it and
+/// diagnostics to run
+///
+/// Works offline; offer it when a `CommandError` has `help_available`.
+#[tauri::command]
+#[specta::specta]
+#[instrument(skip_all, fields(code = ?input.code))]
+pub async fn get_error_help(input: GetErrorHelpInput) -> CommandResponse<ErrorHelp> {
+    Ok(error_help(
+        &input.code,
+        input.yubikey_category.as_ref(),
+        &input.context,
+        input.locale.as_deref(),
+    ))
+}
+
+/// Input for reading one command's log lines
+#[derive(Debug, Deserialize, specta::Type)]
+pub struct GetLogsForTraceInput {
+    /// `trace_id` from a `CommandError` or `ProgressUpdate`
+    pub trace_id: String,
+    /// Most lines to return, latest kept (default 500)
+    #[serde(default)]
+    pub max_lines: Option<u32>,
+}
+
+fn require_trace_id_format(input: &GetLogsForTraceInput) -> Result<(), Box<CommandError>> {
+    if !TraceId::is_valid(&input.trace_id) {
+        return Err(Box::new(invalid(
+            "trace_id",
+            ValidationRule::Format,
+            "Trace ID must be 16 hexadecimal characters",
+        )));
+    }
+    Ok(())
+}
+
+input_rules! {
+    GetLogsForTraceInput {
+        trace_id("Trace ID"): [NonEmptyId],
+    }
+    then require_trace_id_format
+}
+
+/// Log lines written while handling one command invocation
+///
+/// Searches the recent end of the app log, so lines from long-past
+/// invocations may no longer be found.
+#[tauri::command]
+#[specta::specta]
+#[instrument(skip_all, fields(trace_id = %input.trace_id))]
+pub async fn get_logs_for_trace(input: GetLogsForTraceInput) -> CommandResponse<TraceLogLines> {
+    input.validate()?;
+
+    let max_lines = input.max_lines.unwrap_or(DEFAULT_TRACE_LINES) as usize;
+    read_trace_lines(&input.trace_id, max_lines).map_err(|e| {
+        Box::new(
+            CommandError::operation(ErrorCode::StorageFailed, "Failed to read the app log")
+                .with_details(e.to_string()),
+        )
+    })
+}
+
+/// The interface's handshake, sent as its first action
+#[derive(Debug, Deserialize, specta::Type)]
+pub struct FrontendReadyInput {
+    /// Version of the interface build
+    pub ui_version: String,
+    /// `BINDINGS_HASH` from the bindings the interface was built with
+    pub bindings_hash: String,
+}
+
+input_rules! {
+    FrontendReadyInput {
+        ui_version("UI version"): [NonEmpty, MaxLength { max: MAX_HANDSHAKE_FIELD_LENGTH }],
+        bindings_hash("Bindings hash"): [NonEmpty, MaxLength { max: MAX_HANDSHAKE_FIELD_LENGTH }],
+    }
+}
+
+/// Record that the interface loaded and check its bindings match this build
+///
+/// A `drifted` result means the interface was built against other bindings;
+/// commands may fail or misread their responses until the app is reinstalled.
+#[tauri::command]
+#[specta::specta]
+#[instrument(skip_all, fields(ui_version = %input.ui_version))]
+pub async fn frontend_ready(input: FrontendReadyInput) -> CommandResponse<FrontendHandshake> {
+    input.validate()?;
+
+    let expected = bindings_hash();
+    let handshake = VaultManager::new()
+        .record_frontend_ready(&input.ui_version, &input.bindings_hash, expected.as_deref())
+        .map_err(launch_diagnostics_error)?;
+
+    if handshake.drift == BindingsDrift::Drifted {
+        warn!(
+            ui_version = %handshake.ui_version,
+            bindings_hash = %handshake.bindings_hash,
+            expected = ?handshake.expected_bindings_hash,
+            "Interface was built against other bindings"
+        );
+    } else {
+        info!(elapsed_ms = handshake.elapsed.millis(), drift = ?handshake.drift, "Interface loaded");
+    }
+    Ok(handshake)
+}
+
+/// Input for exporting launch diagnostics
+#[derive(Debug, Deserialize, specta::Type)]
+pub struct ExportLaunchDiagnosticsInput {
+    /// Folder to write the diagnostic record into
+    pub destination_dir: String,
+}
+
+input_rules! {
+    ExportLaunchDiagnosticsInput {
+        destination_dir("Destination folder"): [ExistingDirectory],
+    }
+}
+
+/// Write the record of the latest launch whose interface didn't load into a
+/// folder, returning the file's path
+///
+/// Offered by the `launch_failed` notification; dismissing that notification
+/// forgets the record.
+#[tauri::command]
+#[specta::specta]
+#[instrument(skip_all)]
+pub async fn export_launch_diagnostics(
+    input: ExportLaunchDiagnosticsInput,
+) -> CommandResponse<String> {
+    input.validate()?;
+
+    VaultManager::new()
+        .export_launch_diagnostics(std::path::Path::new(&input.destination_dir))
+        .map(|path| path.to_string_lossy().into_owned())
+        .map_err(launch_diagnostics_error)
+}
+
+fn launch_diagnostics_error(e: VaultError) -> Box<CommandError> {
+    match e {
+        VaultError::InvalidOperation(msg) => Box::new(CommandError::validation(msg)),
+        e => Box::new(
+            CommandError::operation(
+                ErrorCode::StorageFailed,
+                "Failed to save launch diagnostics",
+            )
+            .with_details(e.to_string()),
+        ),
+    }
+}
+
+/// Hash of the bindings this build generates, or `None` with a warning
+fn bindings_hash() -> Option<String> {
+    crate::current_bindings_hash()
+        .map_err(|e| warn!(error = %e, "Failed to render bindings for the handshake"))
+        .ok()
+}
+
+/// Start awaiting the interface's handshake
+///
+/// Called from setup. When the startup window closes first, the launch is
+/// recorded as failed. Never fails startup; problems are only logged.
+pub fn start_launch_watchdog() {
+    let timeout = LaunchWatchdog::configured_timeout();
+    let webview_version = tauri::webview_version()
+        .map_err(|e| debug!(error = %e, "Webview version unavailable"))
+        .ok();
+    LaunchWatchdog::global().start(chrono::Utc::now(), timeout, webview_version);
+
+    let spawned = std::thread::Builder::new()
+        .name("launch-watchdog".to_string())
+        .spawn(move || {
+            std::thread::sleep(timeout.into());
+            expire_launch_watchdog();
+        });
+    if let Err(e) = spawned {
+        warn!(error = %e, "Failed to start launch watchdog");
+    }
+}
+
+/// Record the launch as failed if the interface never loaded
+///
+/// Called when the startup window closes and on exit.
+pub fn expire_launch_watchdog() {
+    let watchdog = LaunchWatchdog::global();
+    if watchdog.handshake().is_some() {
+        return;
+    }
+    if let Err(e) = watchdog.expire(chrono::Utc::now(), bindings_hash().as_deref()) {
+        warn!(error = %e, "Failed to record launch diagnostics");
+    }
+}
+
+/// Keep a page load for the launch record, while the handshake is awaited
+pub fn record_page_load(window_label: &str, url: &str, finished: bool) {
+    let kind = if finished {
+        NavigationEventKind::LoadFinished
+    } else {
+        NavigationEventKind::LoadStarted
+    };
+    LaunchWatchdog::global().record_navigation(NavigationEvent {
+        at: chrono::Utc::now(),
+        window_label: window_label.to_string(),
+        kind,
+        url: Some(url.to_string()),
+    });
+}
+
+/// Keep a closed window for the launch record, while the handshake is awaited
+pub fn record_window_closed(window_label: &str) {
+    LaunchWatchdog::global().record_navigation(NavigationEvent {
+        at: chrono::Utc::now(),
+        window_label: window_label.to_string(),
+        kind: NavigationEventKind::WindowClosed,
+        url: None,
+    });
+}
@@ -5,6 +5,7 @@
 
 use super::Manifest;
 use crate::commands::types::{CommandError, CommandResponse, ErrorCode};
+use crate::logging::current_trace_id;
 use crate::prelude::*;
 use crate::services::file::FileManager;
 
@@ -42,8 +43,10 @@ pub async fn create_manifest(file_paths: Vec<String>) -> CommandResponse<Manifes
                 _ => "Check system resources and try again".to_string(),
             }),
             user_actionable: true,
-            trace_id: None,
+            trace_id: current_trace_id(),
             span_id: None,
+            validation: None,
+            help_available: true,
         })),
     }
 }
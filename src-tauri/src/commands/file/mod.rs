@@ -9,13 +9,18 @@
 //! - `select_directory` - Open directory selection dialog
 //! - `get_file_info` - Get information about files/folders
 //! - `create_manifest` - Create manifest for file set
+//! - `compute_upload_metadata` - Compute checksums for S3-compatible uploads
 
 mod manifest;
 mod selection;
+mod upload_metadata;
 
 // Re-export all public commands
 pub use manifest::create_manifest;
 pub use selection::{get_file_info, select_directory, select_files};
+pub use upload_metadata::compute_upload_metadata;
 
 // Re-export data types from domain layer for Tauri bindings
-pub use crate::services::file::domain::models::{FileInfo, FileSelection, Manifest, SelectionType};
+pub use crate::services::file::domain::models::{
+    FileInfo, FileSelection, Manifest, SelectionType, UploadMetadata, UploadPartChecksum,
+};
@@ -4,6 +4,7 @@
 //! and retrieving information about selected items.
 
 use crate::commands::types::{CommandError, CommandResponse, ErrorCode};
+use crate::logging::current_trace_id;
 use tauri::Window;
 use tracing::instrument;
 
@@ -38,8 +39,10 @@ pub async fn select_files(
             details: None,
             recovery_guidance: Some("Check selection type and try again".to_string()),
             user_actionable: true,
-            trace_id: None,
+            trace_id: current_trace_id(),
             span_id: None,
+            validation: None,
+            help_available: true,
         })),
     }
 }
@@ -59,8 +62,10 @@ pub async fn select_directory(title: Option<String>, _window: Window) -> Command
             details: None,
             recovery_guidance: Some("Please type the path manually".to_string()),
             user_actionable: true,
-            trace_id: None,
+            trace_id: current_trace_id(),
             span_id: None,
+            validation: None,
+            help_available: true,
         })),
     }
 }
@@ -88,8 +93,10 @@ pub async fn get_file_info(paths: Vec<String>) -> CommandResponse<Vec<FileInfo>>
             details: None,
             recovery_guidance: Some("Check file paths and permissions".to_string()),
             user_actionable: true,
-            trace_id: None,
+            trace_id: current_trace_id(),
             span_id: None,
+            validation: None,
+            help_available: true,
         })),
     }
 }
@@ -0,0 +1,40 @@
+//! Upload metadata command
+//!
+//! Computes checksums for S3-compatible uploaders. No network access - the
+//! result is handed to the user's own upload tooling.
+
+use super::UploadMetadata;
+use crate::commands::types::{CommandError, CommandResponse, ErrorCode};
+use crate::prelude::*;
+use crate::services::file::FileManager;
+use crate::services::file::domain::FileError;
+use std::path::Path;
+
+/// Compute resumable-upload metadata for an archive
+///
+/// Returns whole-file SHA-256/MD5, per-part MD5s and the composite multipart
+/// ETag for `part_size_mb`. Results are cached in `<archive>.upload.json`.
+#[tauri::command]
+#[specta::specta]
+#[instrument(skip_all, fields(part_size_mb = part_size_mb))]
+pub async fn compute_upload_metadata(
+    archive_path: String,
+    part_size_mb: u64,
+) -> CommandResponse<UploadMetadata> {
+    let manager = FileManager::new();
+
+    match manager
+        .compute_upload_metadata(Path::new(&archive_path), part_size_mb)
+        .await
+    {
+        Ok(metadata) => Ok(metadata),
+        Err(e) => {
+            let code = match e {
+                FileError::ValidationFailed(_) => ErrorCode::InvalidInput,
+                FileError::FileNotFound(_) => ErrorCode::FileNotFound,
+                _ => ErrorCode::FileSystemError,
+            };
+            Err(Box::new(CommandError::operation(code, e.to_string())))
+        }
+    }
+}
@@ -4,6 +4,8 @@
 //! Recipients are public keys belonging to OTHER people that the user
 //! wants to encrypt data FOR (R2.2 feature).
 
+use crate::commands::validation::invalid;
+use crate::logging::current_trace_id;
 use crate::services::key_management::shared::application::services::KeyRegistryService;
 use crate::services::key_management::shared::domain::models::key_lifecycle::{
     KeyLifecycleStatus, StatusHistoryEntry,
@@ -13,10 +15,10 @@
     RecipientValidationError, validate_label, validate_public_key,
 };
 use crate::services::key_management::shared::infrastructure::KeyEntry;
-use crate::types::{CommandError, CommandResponse, ErrorCode};
+use crate::types::{CommandError, CommandResponse, ErrorCode, ValidationRule};
 use chrono::Utc;
 use serde::{Deserialize, Serialize};
-use tracing::{debug, error, info};
+use tracing::{debug, error, info, instrument};
 
 /// Request to add a recipient to the registry
 #[derive(Debug, Clone, Serialize, Deserialize, specta::Type)]
@@ -42,6 +44,7 @@ pub struct AddRecipientResponse {
 /// Recipients can only be used for encryption - the user cannot decrypt with them.
 #[tauri::command]
 #[specta::specta]
+#[instrument(skip_all)]
 pub async fn add_recipient(request: AddRecipientRequest) -> CommandResponse<AddRecipientResponse> {
     debug!(
         label = %request.label,
@@ -76,11 +79,32 @@ pub async fn add_recipient(request: AddRecipientRequest) -> CommandResponse<AddR
                 "Use the existing key or provide a different public key".to_string(),
             ),
             user_actionable: true,
-            trace_id: None,
+            trace_id: current_trace_id(),
             span_id: None,
+            validation: None,
+            help_available: true,
         }));
     }
 
+    // A contact with this key can't also be a recipient key
+    if let Ok(registry) = registry_service.load_registry()
+        && let Some(contact) = registry
+            .contacts
+            .values()
+            .find(|contact| contact.recipient == public_key)
+    {
+        return Err(Box::new(
+            CommandError::operation(
+                ErrorCode::KeyAlreadyExists,
+                format!(
+                    "This public key is already saved as contact '{}'",
+                    contact.name
+                ),
+            )
+            .with_recovery_guidance("Use the existing contact or provide a different public key"),
+        ));
+    }
+
     // Generate key ID from label (sanitized)
     let key_id = generate_recipient_key_id(&label);
 
@@ -111,8 +135,10 @@ pub async fn add_recipient(request: AddRecipientRequest) -> CommandResponse<AddR
             details: None,
             recovery_guidance: Some("Check storage permissions and try again".to_string()),
             user_actionable: true,
-            trace_id: None,
+            trace_id: current_trace_id(),
             span_id: None,
+            validation: None,
+            help_available: true,
         }));
     }
 
@@ -153,39 +179,34 @@ fn generate_recipient_key_id(label: &str) -> String {
     format!("recipient-{}-{}", sanitized.trim_matches('-'), timestamp)
 }
 
-/// Map validation errors to CommandError
-fn map_validation_error(e: RecipientValidationError, field: &str) -> Box<CommandError> {
-    let (code, recovery) = match &e {
+/// Map validation errors to a field-level validation error
+pub(crate) fn map_validation_error(e: RecipientValidationError, field: &str) -> Box<CommandError> {
+    let (rule, recovery) = match &e {
         RecipientValidationError::InvalidPublicKeyPrefix => {
-            (ErrorCode::InvalidInput, "Public key must start with 'age1'")
+            (ValidationRule::Format, "Public key must start with 'age1'")
         }
         RecipientValidationError::InvalidPublicKeyLength(_) => (
-            ErrorCode::InvalidInput,
+            ValidationRule::Format,
             "Public key must be 62-128 characters (62 for standard, 71 for YubiKey)",
         ),
         RecipientValidationError::InvalidPublicKeyCharacters => (
-            ErrorCode::InvalidInput,
+            ValidationRule::Format,
             "Public key contains invalid characters",
         ),
-        RecipientValidationError::LabelEmpty => (ErrorCode::InvalidInput, "Label cannot be empty"),
+        RecipientValidationError::LabelEmpty => (ValidationRule::NonEmpty, "Label cannot be empty"),
         RecipientValidationError::LabelTooLong => (
-            ErrorCode::InvalidInput,
+            ValidationRule::LabelFormat,
             "Label must be 128 characters or less",
         ),
-        RecipientValidationError::LabelInvalidCharacters => {
-            (ErrorCode::InvalidInput, "Label contains invalid characters")
-        }
+        RecipientValidationError::LabelInvalidCharacters => (
+            ValidationRule::LabelFormat,
+            "Label contains invalid characters",
+        ),
     };
 
-    Box::new(CommandError {
-        code,
-        message: format!("Invalid {}: {}", field, e),
-        details: None,
-        recovery_guidance: Some(recovery.to_string()),
-        user_actionable: true,
-        trace_id: None,
-        span_id: None,
-    })
+    Box::new(
+        invalid(field, rule, format!("Invalid {}: {}", field, e)).with_recovery_guidance(recovery),
+    )
 }
 
 #[cfg(test)]
@@ -2,10 +2,13 @@
 //!
 //! Commands for attaching orphaned keys to vaults (R2 API)
 
+use crate::commands::validation::{ExistingVaultId, NonEmptyId, input_rules};
+use crate::commands::vault::refresh_onboarding;
+use crate::logging::current_trace_id;
 use crate::services::key_management::shared::application::manager::KeyManager;
-use crate::types::{CommandError, CommandResponse, ErrorCode};
+use crate::types::{CommandError, CommandResponse, ErrorCode, ValidateInput};
 use serde::{Deserialize, Serialize};
-use tracing::{debug, error, info};
+use tracing::{debug, error, info, instrument};
 
 /// Request to attach a key to a vault
 #[derive(Debug, Clone, Serialize, Deserialize, specta::Type)]
@@ -16,6 +19,13 @@ pub struct AttachKeyToVaultRequest {
     pub vault_id: String,
 }
 
+input_rules! {
+    AttachKeyToVaultRequest {
+        key_id("Key ID"): [NonEmptyId],
+        vault_id("Vault ID"): [ExistingVaultId],
+    }
+}
+
 /// Response from key attachment
 #[derive(Debug, Clone, Serialize, Deserialize, specta::Type)]
 pub struct AttachKeyToVaultResponse {
@@ -31,6 +41,7 @@ pub struct AttachKeyToVaultResponse {
 /// It validates the key state, checks vault limits, and updates both registry and manifest.
 #[tauri::command]
 #[specta::specta]
+#[instrument(skip_all)]
 pub async fn attach_key_to_vault(
     request: AttachKeyToVaultRequest,
 ) -> CommandResponse<AttachKeyToVaultResponse> {
@@ -41,29 +52,7 @@ pub async fn attach_key_to_vault(
     );
 
     // Validate input
-    if request.key_id.is_empty() {
-        return Err(Box::new(CommandError {
-            code: ErrorCode::InvalidInput,
-            message: "Key ID cannot be empty".to_string(),
-            details: None,
-            recovery_guidance: Some("Provide a valid key ID".to_string()),
-            user_actionable: true,
-            trace_id: None,
-            span_id: None,
-        }));
-    }
-
-    if request.vault_id.is_empty() {
-        return Err(Box::new(CommandError {
-            code: ErrorCode::InvalidInput,
-            message: "Vault ID cannot be empty".to_string(),
-            details: None,
-            recovery_guidance: Some("Provide a valid vault ID".to_string()),
-            user_actionable: true,
-            trace_id: None,
-            span_id: None,
-        }));
-    }
+    request.validate()?;
 
     // Use KeyManager to attach the key
     let manager = KeyManager::new();
@@ -78,6 +67,7 @@ pub async fn attach_key_to_vault(
                 vault_id = %request.vault_id,
                 "Successfully attached key to vault"
             );
+            refresh_onboarding().await;
 
             Ok(AttachKeyToVaultResponse {
                 success: true,
@@ -129,8 +119,10 @@ pub async fn attach_key_to_vault(
                 details: Some(error_str),
                 recovery_guidance,
                 user_actionable: true,
-                trace_id: None,
+                trace_id: current_trace_id(),
                 span_id: None,
+                validation: None,
+                help_available: true,
             }))
         }
     }
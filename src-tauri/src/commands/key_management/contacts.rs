@@ -0,0 +1,108 @@
+//! Contact Commands
+//!
+//! Contacts are other people's recipients, added to a single encryption
+//! with `contact_ids`. Unlike recipient keys they are never attached to a
+//! vault, and they can never decrypt.
+
+use super::add_recipient::map_validation_error;
+use crate::commands::validation::{MaxLength, NonEmpty, NonEmptyId, input_rules};
+use crate::services::key_management::shared::domain::models::contact::{
+    ContactError, ContactInfo, MAX_CONTACT_NOTE_LENGTH,
+};
+use crate::services::key_management::shared::{ContactService, KeyManagementError};
+use crate::types::{CommandError, CommandResponse, ErrorCode, ValidateInput};
+use serde::{Deserialize, Serialize};
+use tracing::{error, instrument};
+
+/// Request to add a contact
+#[derive(Debug, Clone, Serialize, Deserialize, specta::Type)]
+pub struct AddContactRequest {
+    pub name: String,
+    /// The contact's age recipient (age1...)
+    pub recipient: String,
+    /// Where the recipient came from, e.g. "read out on a call, 3 May"
+    pub note: Option<String>,
+}
+
+input_rules! {
+    AddContactRequest {
+        name("Contact name"): [NonEmpty],
+        recipient("Recipient"): [NonEmpty],
+        note("Note"): [MaxLength { max: MAX_CONTACT_NOTE_LENGTH }],
+    }
+}
+
+/// Request to remove a contact
+#[derive(Debug, Clone, Serialize, Deserialize, specta::Type)]
+pub struct RemoveContactRequest {
+    pub contact_id: String,
+}
+
+input_rules! {
+    RemoveContactRequest {
+        contact_id("Contact ID"): [NonEmptyId],
+    }
+}
+
+fn contact_error(e: KeyManagementError) -> Box<CommandError> {
+    error!(error = %e, "Contact operation failed");
+    match e {
+        KeyManagementError::Contact(ContactError::InvalidName(e)) => {
+            map_validation_error(e, "name")
+        }
+        KeyManagementError::Contact(ContactError::InvalidRecipient(e)) => {
+            map_validation_error(e, "recipient")
+        }
+        KeyManagementError::Contact(
+            e @ (ContactError::OwnKey(_) | ContactError::DuplicateRecipient(_)),
+        ) => Box::new(
+            CommandError::operation(ErrorCode::KeyAlreadyExists, e.to_string())
+                .with_recovery_guidance("Use the existing entry instead of adding it again"),
+        ),
+        KeyManagementError::Contact(e @ ContactError::NotFound(_)) => Box::new(
+            CommandError::operation(ErrorCode::KeyNotFound, e.to_string())
+                .with_recovery_guidance("Refresh the contact list and try again"),
+        ),
+        e => Box::new(
+            CommandError::operation(ErrorCode::StorageFailed, e.to_string())
+                .with_recovery_guidance("Check storage permissions and try again"),
+        ),
+    }
+}
+
+/// Add a contact
+///
+/// Refuses a recipient that is one of the user's own keys or is already
+/// saved. The response carries the fingerprint to confirm with the contact.
+#[tauri::command]
+#[specta::specta]
+#[instrument(skip(request))]
+pub async fn add_contact(request: AddContactRequest) -> CommandResponse<ContactInfo> {
+    request.validate()?;
+
+    ContactService::new()
+        .add_contact(&request.name, &request.recipient, request.note.as_deref())
+        .map_err(contact_error)
+}
+
+/// List contacts, sorted by name
+#[tauri::command]
+#[specta::specta]
+#[instrument]
+pub async fn list_contacts() -> CommandResponse<Vec<ContactInfo>> {
+    ContactService::new().list_contacts().map_err(contact_error)
+}
+
+/// Remove a contact
+///
+/// Archives already encrypted to the contact keep it in their manifest.
+#[tauri::command]
+#[specta::specta]
+#[instrument]
+pub async fn remove_contact(request: RemoveContactRequest) -> CommandResponse<()> {
+    request.validate()?;
+
+    ContactService::new()
+        .remove_contact(&request.contact_id)
+        .map_err(contact_error)
+}
@@ -2,14 +2,18 @@
 //!
 //! Commands for deactivating keys with a 30-day grace period before permanent deletion
 
+use super::key_removal_plan::require_removal_confirmation;
+use crate::commands::validation::{NonEmptyId, input_rules};
+use crate::logging::current_trace_id;
 use crate::services::key_management::shared::domain::models::key_lifecycle::KeyLifecycleStatus;
 use crate::services::key_management::shared::infrastructure::KeyRegistry;
 use crate::services::shared::infrastructure::path_management::get_keys_dir;
-use crate::types::{CommandError, CommandResponse, ErrorCode};
+use crate::services::shared::infrastructure::{ChangeEvent, publish};
+use crate::types::{CommandError, CommandResponse, ErrorCode, ValidateInput};
 use chrono::{Duration, Utc};
 use serde::{Deserialize, Serialize};
 use std::fs;
-use tracing::{debug, error, info, warn};
+use tracing::{debug, error, info, instrument, warn};
 
 /// Request to deactivate a key
 #[derive(Debug, Clone, Serialize, Deserialize, specta::Type)]
@@ -22,6 +26,16 @@ pub struct DeactivateKeyRequest {
     /// If false or None, use normal deactivation with 30-day grace period
     #[serde(default)]
     pub delete_immediately: Option<bool>,
+    /// Hash of the removal plan shown to the user; required when archives
+    /// would be left unreadable
+    #[serde(default)]
+    pub plan_hash: Option<String>,
+}
+
+input_rules! {
+    DeactivateKeyRequest {
+        key_id("Key ID"): [NonEmptyId],
+    }
 }
 
 /// Response from key deactivation
@@ -46,8 +60,12 @@ pub struct DeactivateKeyResponse {
 ///
 /// For attached keys (Active state), both modes are available based on user choice.
 /// This operation is idempotent - deactivating an already deactivated key returns success.
+///
+/// When archives have this key as their only recipient, `plan_hash` must be
+/// the hash of the current plan from `plan_key_removal`, in both modes.
 #[tauri::command]
 #[specta::specta]
+#[instrument(skip_all)]
 pub async fn deactivate_key(
     request: DeactivateKeyRequest,
 ) -> CommandResponse<DeactivateKeyResponse> {
@@ -61,17 +79,7 @@ pub async fn deactivate_key(
     );
 
     // Validate input
-    if request.key_id.is_empty() {
-        return Err(Box::new(CommandError {
-            code: ErrorCode::InvalidInput,
-            message: "Key ID cannot be empty".to_string(),
-            details: None,
-            recovery_guidance: Some("Provide a valid key ID".to_string()),
-            user_actionable: true,
-            trace_id: None,
-            span_id: None,
-        }));
-    }
+    request.validate()?;
 
     // Load registry
     let mut registry = KeyRegistry::load().map_err(|e| {
@@ -82,8 +90,10 @@ pub async fn deactivate_key(
             details: Some(e.to_string()),
             recovery_guidance: Some("Check system logs or try again".to_string()),
             user_actionable: false,
-            trace_id: None,
+            trace_id: current_trace_id(),
             span_id: None,
+            validation: None,
+            help_available: true,
         })
     })?;
 
@@ -95,8 +105,10 @@ pub async fn deactivate_key(
             details: None,
             recovery_guidance: Some("Verify the key ID is correct".to_string()),
             user_actionable: true,
-            trace_id: None,
+            trace_id: current_trace_id(),
             span_id: None,
+            validation: None,
+            help_available: true,
         })
     })?;
 
@@ -123,6 +135,9 @@ pub async fn deactivate_key(
             });
         }
 
+        // Archives only this key opens must have been shown to the user
+        require_removal_confirmation(&request.key_id, request.plan_hash.clone()).await?;
+
         // Get file path before destroying (if passphrase key)
         let key_file_path = if let Some(filename) = key_entry.passphrase_filename() {
             Some(
@@ -135,8 +150,10 @@ pub async fn deactivate_key(
                             details: Some(e.to_string()),
                             recovery_guidance: None,
                             user_actionable: false,
-                            trace_id: None,
+                            trace_id: current_trace_id(),
                             span_id: None,
+                            validation: None,
+                            help_available: true,
                         })
                     })?
                     .join(filename),
@@ -160,8 +177,10 @@ pub async fn deactivate_key(
                     details: None,
                     recovery_guidance: Some("Check key state and try again".to_string()),
                     user_actionable: true,
-                    trace_id: None,
+                    trace_id: current_trace_id(),
                     span_id: None,
+                    validation: None,
+                    help_available: true,
                 })
             })?;
 
@@ -176,11 +195,18 @@ pub async fn deactivate_key(
                 details: Some(e.to_string()),
                 recovery_guidance: Some("Try again or check system logs".to_string()),
                 user_actionable: false,
-                trace_id: None,
+                trace_id: current_trace_id(),
                 span_id: None,
+                validation: None,
+                help_available: true,
             })
         })?;
 
+        publish(ChangeEvent::KeyDeactivated {
+            key_id: request.key_id.clone(),
+            status: KeyLifecycleStatus::Destroyed,
+        });
+
         // Delete key file (if passphrase key)
         if let Some(file_path) = key_file_path
             && file_path.exists()
@@ -246,6 +272,8 @@ pub async fn deactivate_key(
         });
     }
 
+    require_removal_confirmation(&request.key_id, request.plan_hash.clone()).await?;
+
     // Deactivate the key
     let reason = request
         .reason
@@ -262,8 +290,10 @@ pub async fn deactivate_key(
                     "Only Active or Suspended keys can be deactivated".to_string(),
                 ),
                 user_actionable: true,
-                trace_id: None,
+                trace_id: current_trace_id(),
                 span_id: None,
+                validation: None,
+                help_available: true,
             })
         })?;
 
@@ -275,8 +305,10 @@ pub async fn deactivate_key(
             details: None,
             recovery_guidance: None,
             user_actionable: false,
-            trace_id: None,
+            trace_id: current_trace_id(),
             span_id: None,
+            validation: None,
+            help_available: true,
         })
     })?;
 
@@ -291,11 +323,18 @@ pub async fn deactivate_key(
             details: Some(e.to_string()),
             recovery_guidance: Some("Try again or check system logs".to_string()),
             user_actionable: false,
-            trace_id: None,
+            trace_id: current_trace_id(),
             span_id: None,
+            validation: None,
+            help_available: true,
         })
     })?;
 
+    publish(ChangeEvent::KeyDeactivated {
+        key_id: request.key_id.clone(),
+        status: KeyLifecycleStatus::Deactivated,
+    });
+
     info!(
         key_id = %request.key_id,
         deactivated_at = %deactivated_at.to_rfc3339(),
@@ -322,6 +361,7 @@ fn test_request_validation() {
             key_id: "".to_string(),
             reason: None,
             delete_immediately: None,
+            plan_hash: None,
         };
         assert!(request.key_id.is_empty());
 
@@ -329,6 +369,7 @@ fn test_request_validation() {
             key_id: "test-key".to_string(),
             reason: Some("No longer needed".to_string()),
             delete_immediately: Some(false),
+            plan_hash: None,
         };
         assert!(!request.key_id.is_empty());
         assert_eq!(request.reason, Some("No longer needed".to_string()));
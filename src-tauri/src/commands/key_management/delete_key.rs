@@ -2,14 +2,18 @@
 //!
 //! Commands for permanently deleting keys (immediate destruction)
 
+use super::key_removal_plan::require_removal_confirmation;
+use crate::commands::validation::{NonEmptyId, input_rules};
+use crate::logging::current_trace_id;
 use crate::services::key_management::shared::domain::models::key_lifecycle::KeyLifecycleStatus;
 use crate::services::key_management::shared::infrastructure::KeyRegistry;
 use crate::services::shared::infrastructure::path_management::get_keys_dir;
-use crate::types::{CommandError, CommandResponse, ErrorCode};
+use crate::services::shared::infrastructure::{ChangeEvent, publish};
+use crate::types::{CommandError, CommandResponse, ErrorCode, ValidateInput};
 use chrono::Utc;
 use serde::{Deserialize, Serialize};
 use std::fs;
-use tracing::{debug, error, info, warn};
+use tracing::{debug, error, info, instrument, warn};
 
 /// Request to delete a key permanently
 #[derive(Debug, Clone, Serialize, Deserialize, specta::Type)]
@@ -18,6 +22,16 @@ pub struct DeleteKeyRequest {
     pub key_id: String,
     /// Reason for deletion (optional, for audit trail)
     pub reason: Option<String>,
+    /// Hash of the removal plan shown to the user; required when archives
+    /// would be left unreadable
+    #[serde(default)]
+    pub plan_hash: Option<String>,
+}
+
+input_rules! {
+    DeleteKeyRequest {
+        key_id("Key ID"): [NonEmptyId],
+    }
 }
 
 /// Response from key deletion
@@ -40,8 +54,12 @@ pub struct DeleteKeyResponse {
 /// For attached keys, consider using deactivateKey with delete_immediately flag.
 ///
 /// IMPORTANT: This does NOT un-encrypt vaults. Any backups of the key file can still decrypt vaults.
+///
+/// When archives have this key as their only recipient, `plan_hash` must be
+/// the hash of the current plan from `plan_key_removal`.
 #[tauri::command]
 #[specta::specta]
+#[instrument(skip_all)]
 pub async fn delete_key(request: DeleteKeyRequest) -> CommandResponse<DeleteKeyResponse> {
     debug!(
         key_id = %request.key_id,
@@ -50,17 +68,7 @@ pub async fn delete_key(request: DeleteKeyRequest) -> CommandResponse<DeleteKeyR
     );
 
     // Validate input
-    if request.key_id.is_empty() {
-        return Err(Box::new(CommandError {
-            code: ErrorCode::InvalidInput,
-            message: "Key ID cannot be empty".to_string(),
-            details: None,
-            recovery_guidance: Some("Provide a valid key ID".to_string()),
-            user_actionable: true,
-            trace_id: None,
-            span_id: None,
-        }));
-    }
+    request.validate()?;
 
     // Load registry
     let mut registry = KeyRegistry::load().map_err(|e| {
@@ -71,8 +79,10 @@ pub async fn delete_key(request: DeleteKeyRequest) -> CommandResponse<DeleteKeyR
             details: Some(e.to_string()),
             recovery_guidance: Some("Check system logs or try again".to_string()),
             user_actionable: false,
-            trace_id: None,
+            trace_id: current_trace_id(),
             span_id: None,
+            validation: None,
+            help_available: true,
         })
     })?;
 
@@ -84,8 +94,10 @@ pub async fn delete_key(request: DeleteKeyRequest) -> CommandResponse<DeleteKeyR
             details: None,
             recovery_guidance: Some("Verify the key ID is correct".to_string()),
             user_actionable: true,
-            trace_id: None,
+            trace_id: current_trace_id(),
             span_id: None,
+            validation: None,
+            help_available: true,
         })
     })?;
 
@@ -104,6 +116,9 @@ pub async fn delete_key(request: DeleteKeyRequest) -> CommandResponse<DeleteKeyR
         });
     }
 
+    // Archives only this key opens must have been shown to the user
+    require_removal_confirmation(&request.key_id, request.plan_hash.clone()).await?;
+
     // Get file path before destroying (if passphrase key)
     let key_file_path = if let Some(filename) = key_entry.passphrase_filename() {
         Some(
@@ -116,8 +131,10 @@ pub async fn delete_key(request: DeleteKeyRequest) -> CommandResponse<DeleteKeyR
                         details: Some(e.to_string()),
                         recovery_guidance: None,
                         user_actionable: false,
-                        trace_id: None,
+                        trace_id: current_trace_id(),
                         span_id: None,
+                        validation: None,
+                        help_available: true,
                     })
                 })?
                 .join(filename),
@@ -160,8 +177,10 @@ pub async fn delete_key(request: DeleteKeyRequest) -> CommandResponse<DeleteKeyR
                     details: None,
                     recovery_guidance: Some("Check key state and try again".to_string()),
                     user_actionable: true,
-                    trace_id: None,
+                    trace_id: current_trace_id(),
                     span_id: None,
+                    validation: None,
+                    help_available: true,
                 })
             })?;
     }
@@ -176,11 +195,18 @@ pub async fn delete_key(request: DeleteKeyRequest) -> CommandResponse<DeleteKeyR
             details: Some(e.to_string()),
             recovery_guidance: Some("Try again or check system logs".to_string()),
             user_actionable: false,
-            trace_id: None,
+            trace_id: current_trace_id(),
             span_id: None,
+            validation: None,
+            help_available: true,
         })
     })?;
 
+    publish(ChangeEvent::KeyDeactivated {
+        key_id: request.key_id.clone(),
+        status: KeyLifecycleStatus::Destroyed,
+    });
+
     // Delete the key file from disk (for passphrase keys only)
     // If this fails, registry still shows Destroyed (user can manually clean up file)
     if let Some(file_path) = key_file_path {
@@ -235,12 +261,14 @@ fn test_request_validation() {
         let request = DeleteKeyRequest {
             key_id: "".to_string(),
             reason: None,
+            plan_hash: None,
         };
         assert!(request.key_id.is_empty());
 
         let request = DeleteKeyRequest {
             key_id: "test-key".to_string(),
             reason: Some("No longer needed".to_string()),
+            plan_hash: None,
         };
         assert!(!request.key_id.is_empty());
         assert_eq!(request.reason, Some("No longer needed".to_string()));
@@ -2,12 +2,16 @@
 //!
 //! Commands for exporting passphrase key files to user-selected locations
 
-use crate::services::key_management::shared::infrastructure::KeyRegistry;
-use crate::services::shared::infrastructure::path_management::get_keys_dir;
-use crate::types::{CommandError, CommandResponse, ErrorCode};
+use crate::commands::validation::{NonEmpty, NonEmptyId, input_rules};
+use crate::error::StorageError;
+use crate::logging::current_trace_id;
+use crate::services::key_management::shared::infrastructure::{
+    KeyRegistry, SystemVolumes, resolve_key_file,
+};
+use crate::types::{ByteSize, CommandError, CommandResponse, ErrorCode, ValidateInput};
 use serde::{Deserialize, Serialize};
 use std::fs;
-use tracing::{debug, error, info};
+use tracing::{debug, error, info, instrument};
 
 /// Request to export a key to a destination path
 #[derive(Debug, Clone, Serialize, Deserialize, specta::Type)]
@@ -18,14 +22,21 @@ pub struct ExportKeyRequest {
     pub destination_path: String,
 }
 
+input_rules! {
+    ExportKeyRequest {
+        key_id("Key ID"): [NonEmptyId],
+        destination_path("Destination path"): [NonEmpty],
+    }
+}
+
 /// Response from key export
 #[derive(Debug, Clone, Serialize, Deserialize, specta::Type)]
 pub struct ExportKeyResponse {
     pub success: bool,
     /// The path where the file was exported
     pub exported_file: String,
-    /// Size of the exported file in bytes
-    pub file_size: u64,
+    /// Size of the exported file
+    pub file_size: ByteSize,
 }
 
 /// Export a passphrase key file to a user-selected destination
@@ -37,6 +48,7 @@ pub struct ExportKeyResponse {
 /// show the Export button for passphrase keys, not YubiKey keys (which don't have .enc files).
 #[tauri::command]
 #[specta::specta]
+#[instrument(skip_all)]
 pub async fn export_key(request: ExportKeyRequest) -> CommandResponse<ExportKeyResponse> {
     debug!(
         key_id = %request.key_id,
@@ -45,29 +57,7 @@ pub async fn export_key(request: ExportKeyRequest) -> CommandResponse<ExportKeyR
     );
 
     // Validate input
-    if request.key_id.is_empty() {
-        return Err(Box::new(CommandError {
-            code: ErrorCode::InvalidInput,
-            message: "Key ID cannot be empty".to_string(),
-            details: None,
-            recovery_guidance: Some("Provide a valid key ID".to_string()),
-            user_actionable: true,
-            trace_id: None,
-            span_id: None,
-        }));
-    }
-
-    if request.destination_path.is_empty() {
-        return Err(Box::new(CommandError {
-            code: ErrorCode::InvalidInput,
-            message: "Destination path cannot be empty".to_string(),
-            details: None,
-            recovery_guidance: Some("Provide a valid destination path".to_string()),
-            user_actionable: true,
-            trace_id: None,
-            span_id: None,
-        }));
-    }
+    request.validate()?;
 
     // Load registry
     let registry = KeyRegistry::load().map_err(|e| {
@@ -78,8 +68,10 @@ pub async fn export_key(request: ExportKeyRequest) -> CommandResponse<ExportKeyR
             details: Some(e.to_string()),
             recovery_guidance: Some("Check system logs or try again".to_string()),
             user_actionable: false,
-            trace_id: None,
+            trace_id: current_trace_id(),
             span_id: None,
+            validation: None,
+            help_available: true,
         })
     })?;
 
@@ -91,8 +83,10 @@ pub async fn export_key(request: ExportKeyRequest) -> CommandResponse<ExportKeyR
             details: None,
             recovery_guidance: Some("Verify the key ID is correct".to_string()),
             user_actionable: true,
-            trace_id: None,
+            trace_id: current_trace_id(),
             span_id: None,
+            validation: None,
+            help_available: true,
         })
     })?;
 
@@ -107,26 +101,24 @@ pub async fn export_key(request: ExportKeyRequest) -> CommandResponse<ExportKeyR
             ),
             recovery_guidance: Some("Export is only available for passphrase keys".to_string()),
             user_actionable: true,
-            trace_id: None,
-            span_id: None,
-        })
-    })?;
-
-    // Resolve source path
-    let keys_dir = get_keys_dir().map_err(|e| {
-        error!(error = %e, "Failed to get keys directory");
-        Box::new(CommandError {
-            code: ErrorCode::InternalError,
-            message: "Failed to get keys directory".to_string(),
-            details: Some(e.to_string()),
-            recovery_guidance: None,
-            user_actionable: false,
-            trace_id: None,
+            trace_id: current_trace_id(),
             span_id: None,
+            validation: None,
+            help_available: true,
         })
     })?;
 
-    let source_path = keys_dir.join(key_filename);
+    // Resolve source path (the keys directory, or wherever the file was relinked)
+    let source_path = resolve_key_file(key_filename, key_entry.key_location(), &SystemVolumes)
+        .map_err(|e| {
+            error!(error = %e, "Failed to resolve key file location");
+            let code = match &e {
+                StorageError::KeyMediaNotPresent { .. } => ErrorCode::KeyMediaNotPresent,
+                StorageError::KeyNotFound(_) => ErrorCode::KeyNotFound,
+                _ => ErrorCode::InternalError,
+            };
+            Box::new(CommandError::operation(code, e.to_string()))
+        })?;
 
     // Verify source file exists
     if !source_path.exists() {
@@ -144,8 +136,10 @@ pub async fn export_key(request: ExportKeyRequest) -> CommandResponse<ExportKeyR
                     .to_string(),
             ),
             user_actionable: false,
-            trace_id: None,
+            trace_id: current_trace_id(),
             span_id: None,
+            validation: None,
+            help_available: true,
         }));
     }
 
@@ -158,8 +152,10 @@ pub async fn export_key(request: ExportKeyRequest) -> CommandResponse<ExportKeyR
             details: Some(e.to_string()),
             recovery_guidance: Some("Check file permissions".to_string()),
             user_actionable: false,
-            trace_id: None,
+            trace_id: current_trace_id(),
             span_id: None,
+            validation: None,
+            help_available: true,
         })
     })?;
 
@@ -181,8 +177,10 @@ pub async fn export_key(request: ExportKeyRequest) -> CommandResponse<ExportKeyR
                 "Check destination path is writable and has sufficient disk space".to_string(),
             ),
             user_actionable: true,
-            trace_id: None,
+            trace_id: current_trace_id(),
             span_id: None,
+            validation: None,
+            help_available: true,
         })
     })?;
 
@@ -196,7 +194,7 @@ pub async fn export_key(request: ExportKeyRequest) -> CommandResponse<ExportKeyR
     Ok(ExportKeyResponse {
         success: true,
         exported_file: request.destination_path.clone(),
-        file_size,
+        file_size: ByteSize(file_size),
     })
 }
 
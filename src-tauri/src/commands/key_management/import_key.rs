@@ -2,11 +2,13 @@
 //!
 //! Commands for importing external .enc key files into the registry (R2 API Phase 4)
 
+use crate::commands::validation::{NonEmpty, input_rules};
+use crate::logging::current_trace_id;
 use crate::services::key_management::shared::application::services::KeyImportService;
 use crate::services::key_management::shared::domain::models::key_reference::VaultKey;
-use crate::types::{CommandError, CommandResponse, ErrorCode};
+use crate::types::{CommandError, CommandResponse, ErrorCode, ValidateInput};
 use serde::{Deserialize, Serialize};
-use tracing::{debug, error, info, warn};
+use tracing::{debug, error, info, instrument, warn};
 
 /// Request to import a key file
 #[derive(Debug, Clone, Serialize, Deserialize, specta::Type)]
@@ -21,6 +23,15 @@ pub struct ImportKeyFileRequest {
     pub attach_to_vault: Option<String>,
     /// Only validate without actually importing (dry-run mode)
     pub validate_only: bool,
+    /// Import as `Label (2)` etc. when the label is taken, instead of failing
+    #[serde(default)]
+    pub auto_rename: bool,
+}
+
+input_rules! {
+    ImportKeyFileRequest {
+        file_path("File path"): [NonEmpty],
+    }
 }
 
 /// Response from key import
@@ -62,11 +73,14 @@ pub struct KeyMetadata {
 /// - Validates age encryption format
 /// - Checks for duplicate keys by comparing public keys
 /// - Sanitizes labels to prevent injection attacks
+/// - Keeps labels unique (auto-suffix, or a conflict error carrying the
+///   existing key ID in `details`)
 /// - Supports dry-run validation mode
 /// - Can immediately attach imported keys to vaults
 /// - Creates audit trail for security compliance
 #[tauri::command]
 #[specta::specta]
+#[instrument(skip_all)]
 pub async fn import_key_file(
     request: ImportKeyFileRequest,
 ) -> CommandResponse<ImportKeyFileResponse> {
@@ -80,17 +94,7 @@ pub async fn import_key_file(
     );
 
     // Validate input
-    if request.file_path.is_empty() {
-        return Err(Box::new(CommandError {
-            code: ErrorCode::InvalidInput,
-            message: "File path cannot be empty".to_string(),
-            details: None,
-            recovery_guidance: Some("Provide a valid path to a .enc key file".to_string()),
-            user_actionable: true,
-            trace_id: None,
-            span_id: None,
-        }));
-    }
+    request.validate()?;
 
     // Validate file extension
     if !request.file_path.ends_with(".enc") && !request.file_path.ends_with(".agekey.enc") {
@@ -111,6 +115,7 @@ pub async fn import_key_file(
             request.override_label,
             request.attach_to_vault.clone(),
             request.validate_only,
+            request.auto_rename,
         )
         .await
     {
@@ -156,7 +161,14 @@ pub async fn import_key_file(
             );
 
             // Map error types to appropriate error codes and guidance
-            let error_str = e.to_string();
+            // (label conflicts carry the existing key ID so the UI can prompt)
+            let error_str = match &e {
+                crate::services::key_management::shared::application::services::ImportError::LabelConflict {
+                    existing_key_id,
+                    ..
+                } => existing_key_id.clone(),
+                _ => e.to_string(),
+            };
             let (code, recovery_guidance) = match e {
                 crate::services::key_management::shared::application::services::ImportError::FileNotFound(_) => (
                     ErrorCode::FileNotFound,
@@ -170,6 +182,10 @@ pub async fn import_key_file(
                     ErrorCode::InvalidInput,
                     Some("Ensure the file is a valid age-encrypted key file (.enc)".to_string()),
                 ),
+                crate::services::key_management::shared::application::services::ImportError::DamagedKeyFile(_) => (
+                    ErrorCode::InvalidFileFormat,
+                    Some("Repair the key file to write a fixed copy beside it, then import the copy".to_string()),
+                ),
                 crate::services::key_management::shared::application::services::ImportError::InvalidKeyData(_) => (
                     ErrorCode::InvalidInput,
                     Some("The key file appears to be corrupted or invalid".to_string()),
@@ -194,6 +210,10 @@ pub async fn import_key_file(
                     ErrorCode::InvalidInput,
                     Some("This key already exists in your registry. Delete the existing key first if you want to replace it.".to_string()),
                 ),
+                crate::services::key_management::shared::application::services::ImportError::LabelConflict { .. } => (
+                    ErrorCode::KeyAlreadyExists,
+                    Some("Choose a different label, or import again with automatic renaming.".to_string()),
+                ),
                 _ => (
                     ErrorCode::UnknownError,
                     Some("An unexpected error occurred during import".to_string()),
@@ -206,8 +226,10 @@ pub async fn import_key_file(
                 details: Some(error_str),
                 recovery_guidance,
                 user_actionable: true,
-                trace_id: None,
+                trace_id: current_trace_id(),
                 span_id: None,
+                validation: None,
+                help_available: true,
             }))
         }
     }
@@ -225,6 +247,7 @@ fn test_request_validation() {
             override_label: None,
             attach_to_vault: None,
             validate_only: false,
+            auto_rename: false,
         };
         assert!(request.file_path.is_empty());
 
@@ -234,6 +257,7 @@ fn test_request_validation() {
             override_label: Some("My Imported Key".to_string()),
             attach_to_vault: Some("vault-123".to_string()),
             validate_only: true,
+            auto_rename: true,
         };
         assert!(!request.file_path.is_empty());
         assert!(request.passphrase.is_some());
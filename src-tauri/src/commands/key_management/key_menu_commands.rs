@@ -25,6 +25,9 @@ pub struct GetKeyMenuDataRequest {
 pub struct GetKeyMenuDataResponse {
     pub vault_id: String,
     pub keys: Vec<VaultKey>,
+    /// Contacts that can be added to an encryption, sorted by name. External:
+    /// never vault keys and never offered for decryption.
+    pub contacts: Vec<VaultKey>,
 }
 
 /// Get structured key menu data for UI display
@@ -195,14 +198,32 @@ pub async fn get_key_menu_data(
         }
     }
 
+    let mut contacts: Vec<VaultKey> = registry
+        .contacts
+        .iter()
+        .map(|(contact_id, contact)| VaultKey {
+            id: contact_id.clone(),
+            label: contact.name.clone(),
+            lifecycle_status: KeyLifecycleStatus::Active,
+            key_type: KeyType::Contact {
+                fingerprint: contact.fingerprint(),
+            },
+            created_at: contact.added_at,
+            last_used: None,
+        })
+        .collect();
+    contacts.sort_by_key(|contact| contact.label.to_lowercase());
+
     debug!(
         vault_id = %input.vault_id,
         keys_count = key_menu_items.len(),
+        contacts_count = contacts.len(),
         "Key menu data prepared successfully"
     );
 
     Ok(GetKeyMenuDataResponse {
         vault_id: input.vault_id,
         keys: key_menu_items,
+        contacts,
     })
 }
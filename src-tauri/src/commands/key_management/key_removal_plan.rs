@@ -0,0 +1,70 @@
+//! Key Removal Plan Command
+//!
+//! Shows which archives would become unreadable if a key were removed now.
+//! `delete_key` and `deactivate_key` take the plan's hash back whenever
+//! archives would be orphaned, so a key can't be removed without the user
+//! having seen them.
+
+use crate::commands::validation::{NonEmptyId, input_rules};
+use crate::logging::spawn_blocking;
+use crate::services::key_management::shared::domain::models::KeyRemovalPlan;
+use crate::services::key_management::shared::{KeyManagementError, KeyManager};
+use crate::types::{CommandError, CommandResponse, ErrorCode, ValidateInput};
+use serde::Deserialize;
+use tracing::{error, instrument};
+
+/// Request to plan a key's removal
+#[derive(Debug, Deserialize, specta::Type)]
+pub struct PlanKeyRemovalRequest {
+    pub key_id: String,
+}
+
+input_rules! {
+    PlanKeyRemovalRequest {
+        key_id("Key ID"): [NonEmptyId],
+    }
+}
+
+/// Which archives, across all vaults, removing a key would orphan or leave
+/// with fewer keys
+#[tauri::command]
+#[specta::specta]
+#[instrument(skip_all, fields(key_id = %request.key_id))]
+pub async fn plan_key_removal(request: PlanKeyRemovalRequest) -> CommandResponse<KeyRemovalPlan> {
+    request.validate()?;
+
+    spawn_blocking(move || KeyManager::new().plan_key_removal(&request.key_id))
+        .await
+        .map_err(|e| CommandError::operation(ErrorCode::InternalError, format!("Task error: {e}")))?
+        .map_err(|e| {
+            error!(error = %e, "Failed to plan key removal");
+            removal_error(e)
+        })
+}
+
+/// Fail unless removing `key_id` orphans nothing or `plan_hash` is its
+/// current removal plan's
+pub(crate) async fn require_removal_confirmation(
+    key_id: &str,
+    plan_hash: Option<String>,
+) -> Result<(), Box<CommandError>> {
+    let key_id = key_id.to_string();
+    spawn_blocking(move || KeyManager::new().confirm_key_removal(&key_id, plan_hash.as_deref()))
+        .await
+        .map_err(|e| CommandError::operation(ErrorCode::InternalError, format!("Task error: {e}")))?
+        .map_err(removal_error)
+}
+
+fn removal_error(e: KeyManagementError) -> Box<CommandError> {
+    let (code, guidance) = match &e {
+        KeyManagementError::KeyNotFound(_) => {
+            (ErrorCode::KeyNotFound, "Verify the key ID is correct")
+        }
+        KeyManagementError::RemovalUnconfirmed(_) => (
+            ErrorCode::KeyRemovalUnconfirmed,
+            "Review the archives this key alone can open, then confirm with the plan shown",
+        ),
+        _ => (ErrorCode::StorageFailed, "Check system logs or try again"),
+    };
+    Box::new(CommandError::operation(code, e.to_string()).with_recovery_guidance(guidance))
+}
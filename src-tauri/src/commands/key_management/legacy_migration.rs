@@ -0,0 +1,31 @@
+//! Legacy Profile Migration Commands
+//!
+//! Reports what the startup migration did with profiles written by older
+//! builds: which layouts were found, which keys were brought over, and any
+//! conflicts with current data that kept it from running.
+
+use crate::services::key_management::shared::application::services::LegacyMigrationService;
+use crate::services::key_management::shared::domain::models::MigrationReport;
+use crate::types::{CommandError, CommandResponse, ErrorCode};
+use tracing::{error, instrument};
+
+/// Report of the last legacy profile migration
+///
+/// `None` when no legacy profile was ever found. A report with status
+/// `conflict` means nothing was migrated; the legacy files are untouched
+/// until the listed conflicts are resolved.
+#[tauri::command]
+#[specta::specta]
+#[instrument(skip_all)]
+pub async fn get_legacy_migration_report() -> CommandResponse<Option<MigrationReport>> {
+    LegacyMigrationService::new().last_report().map_err(|e| {
+        error!(error = %e, "Failed to read legacy migration report");
+        Box::new(
+            CommandError::operation(
+                ErrorCode::StorageFailed,
+                "Failed to read legacy migration report",
+            )
+            .with_details(e.to_string()),
+        )
+    })
+}
@@ -10,15 +10,27 @@
 //! - attach_key.rs: Universal key attachment to vaults (R2 API)
 //! - import_key.rs: Import external .enc key files (R2 API Phase 4)
 //! - add_recipient.rs: Add recipient (public-key-only) entries (R2.2)
+//! - contacts.rs: Contacts, other people's recipients used per encryption
+//! - key_removal_plan.rs: Archives a key's removal would orphan, confirmed before removal
+//! - legacy_migration.rs: Report of the startup migration of older profiles
+//! - normalize_key_labels.rs: Registry maintenance for duplicate key labels
+//! - relink_key_file.rs: Relink passphrase key files moved to removable drives
+//! - replace_yubikey.rs: Guided replacement of a lost YubiKey
 
 pub mod add_recipient;
 pub mod attach_key;
+pub mod contacts;
 pub mod deactivate_key;
 pub mod delete_key;
 pub mod export_key;
 pub mod import_key;
 pub mod key_menu_commands;
+pub mod key_removal_plan;
+pub mod legacy_migration;
+pub mod normalize_key_labels;
 pub mod passphrase;
+pub mod relink_key_file;
+pub mod replace_yubikey;
 pub mod restore_key;
 pub mod unified_keys;
 pub mod update_global_key_label;
@@ -34,16 +46,19 @@
 };
 
 pub use yubikey::{
-    AvailableYubiKey, PinStatus, RegisterYubiKeyForVaultParams, StreamlinedYubiKeyInitResult,
-    UnlockCredentials, YubiKeyInitForVaultParams, YubiKeyState, YubiKeyStateInfo, init_yubikey,
-    init_yubikey_for_vault, list_yubikeys, register_yubikey, register_yubikey_for_vault,
-    yubikey_decrypt_file,
+    AvailableYubiKey, PinStatus, RegisterYubiKeyForVaultParams, ReserveYubiKeySlotParams,
+    StreamlinedYubiKeyInitResult, UnlockCredentials, YubiKeyInitForVaultParams, YubiKeyState,
+    YubiKeyStateInfo, init_yubikey, init_yubikey_for_vault, list_yubikeys, register_yubikey,
+    register_yubikey_for_vault, reserve_yubikey_slot, yubikey_decrypt_file,
 };
 
 pub use key_menu_commands::{GetKeyMenuDataRequest, GetKeyMenuDataResponse, get_key_menu_data};
 
+pub use key_removal_plan::{PlanKeyRemovalRequest, plan_key_removal};
+
 pub use unified_keys::{
-    GlobalKey, KeyListFilter, KeyType, YubiKeyInfo, list_unified_keys, test_unified_keys,
+    GlobalKey, KeyListFilter, KeySortField, KeyType, ListUnifiedKeysResponse, YubiKeyInfo,
+    list_unified_keys, test_unified_keys,
 };
 
 pub use attach_key::{AttachKeyToVaultRequest, AttachKeyToVaultResponse, attach_key_to_vault};
@@ -65,3 +80,15 @@
 };
 
 pub use add_recipient::{AddRecipientRequest, AddRecipientResponse, add_recipient};
+
+pub use contacts::{
+    AddContactRequest, RemoveContactRequest, add_contact, list_contacts, remove_contact,
+};
+
+pub use legacy_migration::get_legacy_migration_report;
+
+pub use normalize_key_labels::{NormalizeKeyLabelsResponse, normalize_key_labels};
+
+pub use relink_key_file::{RelinkKeyFileRequest, RelinkKeyFileResponse, relink_key_file};
+
+pub use replace_yubikey::{ReplaceYubiKeyRequest, replace_yubikey};
@@ -0,0 +1,50 @@
+//! Key Label Normalization Commands
+//!
+//! Maintenance command that makes key labels unique across the registry.
+//! Duplicates are renamed deterministically by creation date and the renames
+//! are reported back to the caller.
+
+use crate::logging::current_trace_id;
+use crate::services::key_management::shared::{KeyRegistryService, LabelRename};
+use crate::types::{CommandError, CommandResponse, ErrorCode};
+use serde::{Deserialize, Serialize};
+use tracing::{error, info, instrument};
+
+/// Response from key label normalization
+#[derive(Debug, Clone, Serialize, Deserialize, specta::Type)]
+pub struct NormalizeKeyLabelsResponse {
+    /// Labels changed to remove duplicates (empty when already unique)
+    pub renamed: Vec<LabelRename>,
+}
+
+/// Rename duplicate key labels so every key has a unique one
+///
+/// The oldest key in each group of duplicates keeps its label; the others
+/// become `Label (2)`, `Label (3)`, ... Vault manifests referencing a renamed
+/// key are updated in the same journaled write.
+#[tauri::command]
+#[specta::specta]
+#[instrument(skip_all)]
+pub async fn normalize_key_labels() -> CommandResponse<NormalizeKeyLabelsResponse> {
+    let renamed = KeyRegistryService::new()
+        .normalize_labels()
+        .await
+        .map_err(|e| {
+            error!(error = %e, "Failed to normalize key labels");
+            Box::new(CommandError {
+                code: ErrorCode::StorageFailed,
+                message: "Failed to normalize key labels".to_string(),
+                details: Some(e.to_string()),
+                recovery_guidance: Some("Check system logs or try again".to_string()),
+                user_actionable: false,
+                trace_id: current_trace_id(),
+                span_id: None,
+                validation: None,
+                help_available: true,
+            })
+        })?;
+
+    info!(renamed = renamed.len(), "Key labels normalized");
+
+    Ok(NormalizeKeyLabelsResponse { renamed })
+}
@@ -1,6 +1,7 @@
 use crate::commands::types::{
-    CommandError, CommandResponse, ErrorCode, ErrorHandler, ValidateInput, ValidationHelper,
+    CommandError, CommandResponse, ErrorCode, ErrorHandler, ValidateInput,
 };
+use crate::commands::validation::{LabelFormat, NonEmpty, PassphraseStrength, input_rules};
 use crate::prelude::*;
 use crate::services::key_management::passphrase::PassphraseManager;
 use serde::{Deserialize, Serialize};
@@ -18,12 +19,10 @@ pub struct GenerateKeyResponse {
     pub saved_path: String,
 }
 
-impl ValidateInput for GenerateKeyInput {
-    fn validate(&self) -> Result<(), Box<CommandError>> {
-        ValidationHelper::validate_not_empty(&self.label, "Key label")?;
-        ValidationHelper::validate_key_label(&self.label)?;
-        ValidationHelper::validate_passphrase_strength(&self.passphrase)?;
-        Ok(())
+input_rules! {
+    GenerateKeyInput {
+        label("Key label"): [NonEmpty, LabelFormat],
+        passphrase("Passphrase"): [PassphraseStrength],
     }
 }
 
@@ -33,9 +32,7 @@ fn validate(&self) -> Result<(), Box<CommandError>> {
 pub async fn generate_key(input: GenerateKeyInput) -> CommandResponse<GenerateKeyResponse> {
     let error_handler = ErrorHandler::new();
 
-    input
-        .validate()
-        .map_err(|e| error_handler.handle_validation_error("input", &e.message))?;
+    input.validate()?;
 
     info!(
         label = %input.label,
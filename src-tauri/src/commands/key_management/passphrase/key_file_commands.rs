@@ -0,0 +1,110 @@
+//! Key file validation and repair commands
+//!
+//! Explains why a hand-edited or emailed key file won't load and writes a
+//! fixed copy beside it. Neither command needs the passphrase.
+
+use crate::commands::types::{CommandError, CommandResponse, ErrorCode, ValidateInput};
+use crate::commands::validation::{NonEmpty, input_rules};
+use crate::prelude::*;
+use crate::services::key_management::passphrase::PassphraseManager;
+use crate::services::key_management::passphrase::application::KeyFileRepairError;
+use crate::services::key_management::passphrase::domain::KeyFileDiagnosis;
+use std::path::Path;
+
+#[derive(Debug, Deserialize, specta::Type)]
+pub struct ValidateKeyFileRequest {
+    pub file_path: String,
+}
+
+input_rules! {
+    ValidateKeyFileRequest {
+        file_path("File path"): [NonEmpty],
+    }
+}
+
+#[derive(Debug, Deserialize, specta::Type)]
+pub struct RepairKeyFileRequest {
+    pub file_path: String,
+}
+
+input_rules! {
+    RepairKeyFileRequest {
+        file_path("File path"): [NonEmpty],
+    }
+}
+
+#[derive(Debug, Serialize, specta::Type)]
+pub struct RepairKeyFileResponse {
+    /// The fixed copy; import or unlock this file
+    pub repaired_path: String,
+    /// What was wrong with the original, all of it fixed in the copy
+    pub fixed: KeyFileDiagnosis,
+}
+
+fn key_file_error(e: KeyFileRepairError) -> Box<CommandError> {
+    let (code, guidance) = match &e {
+        KeyFileRepairError::Io(io) if io.kind() == std::io::ErrorKind::NotFound => (
+            ErrorCode::FileNotFound,
+            "Check that the file path is correct and the file exists",
+        ),
+        KeyFileRepairError::Io(_) => (
+            ErrorCode::StorageFailed,
+            "Check the folder holding the key file is writable",
+        ),
+        KeyFileRepairError::TooLarge(_) => (
+            ErrorCode::FileTooLarge,
+            "Ensure you selected a key file (.agekey.enc)",
+        ),
+        KeyFileRepairError::NothingToRepair => (
+            ErrorCode::InvalidInput,
+            "The file is intact; if it still won't unlock, check the passphrase",
+        ),
+        KeyFileRepairError::NotRepairable(_) => (
+            ErrorCode::InvalidFileFormat,
+            "Use another copy of this key, such as a backup or the recovery kit",
+        ),
+    };
+    Box::new(CommandError::operation(code, e.to_string()).with_recovery_guidance(guidance))
+}
+
+/// Check a key file's structure without unlocking it
+///
+/// Reports byte order marks, CRLF line endings, trailing spaces, rewrapped
+/// lines, ASCII armor and truncation, each marked repairable or not.
+#[tauri::command]
+#[specta::specta]
+#[instrument(skip(input), fields(file_path = %input.file_path))]
+pub async fn validate_key_file(input: ValidateKeyFileRequest) -> CommandResponse<KeyFileDiagnosis> {
+    input.validate()?;
+
+    PassphraseManager::new()
+        .validate_key_file(Path::new(&input.file_path))
+        .map_err(key_file_error)
+}
+
+/// Write a repaired copy of a damaged key file beside it
+///
+/// The original is left as it is. The copy is validated again before it's
+/// returned.
+#[tauri::command]
+#[specta::specta]
+#[instrument(skip(input), fields(file_path = %input.file_path))]
+pub async fn repair_key_file(
+    input: RepairKeyFileRequest,
+) -> CommandResponse<RepairKeyFileResponse> {
+    input.validate()?;
+
+    let repaired = PassphraseManager::new()
+        .repair_key_file(Path::new(&input.file_path))
+        .map_err(key_file_error)?;
+
+    info!(
+        repaired_path = %repaired.repaired_path.display(),
+        fixed = repaired.fixed.issues.len(),
+        "Key file repaired"
+    );
+    Ok(RepairKeyFileResponse {
+        repaired_path: repaired.repaired_path.to_string_lossy().to_string(),
+        fixed: repaired.fixed,
+    })
+}
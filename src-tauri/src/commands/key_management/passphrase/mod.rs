@@ -1,8 +1,17 @@
 pub mod generation_commands;
+pub mod key_file_commands;
+pub mod recovery_share_commands;
 pub mod validation_commands;
 pub mod vault_commands;
 
 pub use generation_commands::{GenerateKeyInput, GenerateKeyResponse, generate_key};
+pub use key_file_commands::{
+    RepairKeyFileRequest, RepairKeyFileResponse, ValidateKeyFileRequest, repair_key_file,
+    validate_key_file,
+};
+pub use recovery_share_commands::{
+    CreateRecoverySharesRequest, CreateRecoverySharesResponse, create_recovery_shares,
+};
 pub use validation_commands::{
     PassphraseValidationResult, ValidatePassphraseInput, ValidatePassphraseResponse,
     VerifyKeyPassphraseInput, VerifyKeyPassphraseResponse, validate_passphrase,
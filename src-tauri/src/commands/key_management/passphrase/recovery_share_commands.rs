@@ -0,0 +1,82 @@
+//! Shamir recovery share commands
+//!
+//! Splits a vault's recovery secret among several holders. Shares are
+//! returned once and never stored; they stay in memory for a few minutes
+//! only so each can be copied to the clipboard for its holder, or until a
+//! panic lock zeroizes them.
+
+use crate::commands::types::{
+    CommandError, CommandResponse, ErrorCode, ValidateInput, ValidationRule,
+};
+use crate::commands::validation::{ExistingVaultId, input_rules, invalid};
+use crate::commands::vault::refresh_onboarding;
+use crate::prelude::*;
+use crate::services::key_management::passphrase::{
+    PassphraseError, PassphraseManager, RecoveryShareError,
+};
+
+#[derive(Debug, Deserialize, specta::Type)]
+pub struct CreateRecoverySharesRequest {
+    pub vault_id: String,
+    pub threshold: u8,
+    pub total: u8,
+}
+
+#[derive(Debug, Serialize, specta::Type)]
+pub struct CreateRecoverySharesResponse {
+    /// Share strings - display once, they cannot be retrieved again
+    pub shares: Vec<String>,
+    pub threshold: u8,
+    pub total: u8,
+    pub recovery_public_key: String,
+}
+
+input_rules! {
+    CreateRecoverySharesRequest {
+        vault_id("Vault ID"): [ExistingVaultId],
+    }
+}
+
+#[tauri::command]
+#[specta::specta]
+#[instrument(skip(input), fields(vault_id = %input.vault_id, threshold = input.threshold, total = input.total))]
+pub async fn create_recovery_shares(
+    input: CreateRecoverySharesRequest,
+) -> CommandResponse<CreateRecoverySharesResponse> {
+    input.validate()?;
+
+    let manager = PassphraseManager::new();
+    let created = manager
+        .create_recovery_shares(&input.vault_id, input.threshold, input.total)
+        .await
+        .map_err(|e| match e {
+            RecoveryShareError::Share(PassphraseError::InvalidInput(msg)) => {
+                Box::new(invalid("threshold", ValidationRule::InRange, msg))
+            }
+            RecoveryShareError::VaultNotFound(_) => Box::new(CommandError::operation(
+                ErrorCode::VaultNotFound,
+                e.to_string(),
+            )),
+            RecoveryShareError::AlreadyConfigured(_) => Box::new(
+                CommandError::operation(ErrorCode::KeyAlreadyExists, e.to_string())
+                    .with_recovery_guidance("Recovery shares can only be created once per vault"),
+            ),
+            _ => Box::new(CommandError::operation(
+                ErrorCode::StorageFailed,
+                format!("Failed to create recovery shares: {}", e),
+            )),
+        })?;
+
+    info!(
+        vault_id = %input.vault_id,
+        "Recovery shares created"
+    );
+    refresh_onboarding().await;
+
+    Ok(CreateRecoverySharesResponse {
+        shares: created.shares,
+        threshold: created.threshold,
+        total: created.total,
+        recovery_public_key: created.public_key,
+    })
+}
@@ -1,7 +1,11 @@
 use crate::commands::types::{CommandError, CommandResponse, ValidateInput};
+use crate::commands::validation::{NonEmpty, input_rules};
 use crate::constants::MIN_PASSPHRASE_LENGTH;
-use crate::services::key_management::passphrase::{PassphraseManager, PassphraseStrength};
+use crate::services::key_management::passphrase::{
+    PassphraseManager, PassphraseStrength, normalize_passphrase,
+};
 use serde::{Deserialize, Serialize};
+use tracing::instrument;
 
 #[derive(Debug, Serialize, specta::Type)]
 pub struct PassphraseValidationResult {
@@ -13,6 +17,7 @@ pub struct PassphraseValidationResult {
 
 #[tauri::command]
 #[specta::specta]
+#[instrument(skip_all)]
 pub async fn validate_passphrase_strength(
     passphrase: String,
 ) -> CommandResponse<PassphraseValidationResult> {
@@ -50,23 +55,19 @@ pub struct ValidatePassphraseResponse {
     pub message: String,
 }
 
-impl ValidateInput for ValidatePassphraseInput {
-    fn validate(&self) -> Result<(), Box<CommandError>> {
-        if self.passphrase.is_empty() {
-            return Err(Box::new(CommandError::validation(
-                "Passphrase cannot be empty",
-            )));
-        }
-        Ok(())
+input_rules! {
+    ValidatePassphraseInput {
+        passphrase("Passphrase"): [NonEmpty],
     }
 }
 
 #[tauri::command]
 #[specta::specta]
+#[instrument(skip_all)]
 pub async fn validate_passphrase(
     input: ValidatePassphraseInput,
 ) -> CommandResponse<ValidatePassphraseResponse> {
-    let passphrase = &input.passphrase;
+    let passphrase = normalize_passphrase(&input.passphrase);
 
     if passphrase.len() < MIN_PASSPHRASE_LENGTH {
         return Ok(ValidatePassphraseResponse {
@@ -103,11 +104,14 @@ pub async fn validate_passphrase(
 
 #[tauri::command]
 #[specta::specta]
+#[instrument(skip_all)]
 pub async fn verify_key_passphrase(
     input: VerifyKeyPassphraseInput,
 ) -> CommandResponse<VerifyKeyPassphraseResponse> {
     use crate::prelude::*;
-    use crate::services::key_management::passphrase::PassphraseKeyRepository;
+    use crate::services::key_management::passphrase::{
+        PassphraseKeyRepository, StorageError, ValidationError,
+    };
 
     let key_entry = PassphraseKeyRepository::get_key(&input.key_id).map_err(|_| {
         Box::new(CommandError::operation(
@@ -128,6 +132,12 @@ pub async fn verify_key_passphrase(
                     is_valid: false,
                     message: "Incorrect passphrase".to_string(),
                 }),
+                Err(ValidationError::Storage(StorageError::KeyMediaNotPresent(label))) => {
+                    Err(Box::new(CommandError::operation(
+                        ErrorCode::KeyMediaNotPresent,
+                        format!("Insert the '{label}' drive holding this key, then try again"),
+                    )))
+                }
                 Err(_) => Err(Box::new(CommandError::operation(
                     ErrorCode::KeyNotFound,
                     format!("Key '{}' not found", input.key_id),
@@ -188,5 +198,12 @@ pub async fn verify_key_passphrase(
                 "Cannot verify passphrase for a recipient key. Recipients are public keys belonging to other people.".to_string(),
             )))
         }
+        crate::services::key_management::shared::KeyEntry::YubikeyPending { .. } => {
+            Err(Box::new(CommandError::operation(
+                ErrorCode::InvalidKeyState,
+                "Cannot verify a PIN for a reserved YubiKey slot. Register the YubiKey first."
+                    .to_string(),
+            )))
+        }
     }
 }
@@ -1,5 +1,10 @@
-use crate::commands::types::{CommandError, CommandResponse, ErrorCode};
+use crate::commands::types::{CommandError, CommandResponse, ErrorCode, ValidateInput};
+use crate::commands::validation::{
+    ExistingVaultId, LabelFormat, NonEmpty, PassphraseStrength, input_rules,
+};
+use crate::commands::vault::refresh_onboarding;
 use crate::services::key_management::passphrase::PassphraseManager;
+use tracing::instrument;
 
 use crate::services::key_management::shared::domain::models::VaultKey;
 use serde::{Deserialize, Serialize};
@@ -14,6 +19,14 @@ pub struct AddPassphraseKeyRequest {
     pub passphrase: String,
 }
 
+input_rules! {
+    AddPassphraseKeyRequest {
+        vault_id("Vault ID"): [ExistingVaultId],
+        label("Key label"): [NonEmpty, LabelFormat],
+        passphrase("Passphrase"): [PassphraseStrength],
+    }
+}
+
 #[derive(Debug, Serialize, specta::Type)]
 pub struct AddPassphraseKeyResponse {
     pub key_reference: VaultKey,
@@ -27,9 +40,11 @@ pub struct ListPassphraseKeysResponse {
 
 #[tauri::command]
 #[specta::specta]
+#[instrument(skip_all)]
 pub async fn add_passphrase_key_to_vault(
     input: AddPassphraseKeyRequest,
 ) -> CommandResponse<AddPassphraseKeyResponse> {
+    input.validate()?;
     let manager = PassphraseManager::new();
 
     let generated = manager
@@ -56,6 +71,7 @@ pub async fn add_passphrase_key_to_vault(
             )
         })?;
 
+    refresh_onboarding().await;
     Ok(AddPassphraseKeyResponse {
         key_reference,
         public_key: generated.public_key,
@@ -64,6 +80,7 @@ pub async fn add_passphrase_key_to_vault(
 
 #[tauri::command]
 #[specta::specta]
+#[instrument(skip_all)]
 pub async fn validate_vault_passphrase_key(vault_id: String) -> CommandResponse<bool> {
     let manager = PassphraseManager::new();
 
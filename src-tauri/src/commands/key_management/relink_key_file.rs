@@ -0,0 +1,85 @@
+//! Key File Relink Command
+//!
+//! Points a passphrase key at its key file after the file was moved, for
+//! example onto a USB drive. The drive is recorded with the path, so the key
+//! is still found when the drive mounts somewhere else next time.
+
+use crate::commands::validation::{ExistingFile, NonEmpty, NonEmptyId, input_rules};
+use crate::services::key_management::shared::{KeyManagementError, KeyRegistryService};
+use crate::types::{CommandError, CommandResponse, ErrorCode, ValidateInput};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use tracing::{error, info, instrument};
+
+/// Request to relink a passphrase key's file
+#[derive(Debug, Clone, Serialize, Deserialize, specta::Type)]
+pub struct RelinkKeyFileRequest {
+    /// The passphrase key to relink
+    pub key_id: String,
+    /// Full path of the key file's new location
+    pub new_path: String,
+}
+
+/// Response from relinking a key file
+#[derive(Debug, Clone, Serialize, Deserialize, specta::Type)]
+pub struct RelinkKeyFileResponse {
+    pub key_id: String,
+    /// Resolved path of the key file
+    pub key_path: String,
+    /// Label of the drive holding the file (None when not on a mounted volume)
+    pub volume_label: Option<String>,
+}
+
+input_rules! {
+    RelinkKeyFileRequest {
+        key_id("Key ID"): [NonEmptyId],
+        new_path("Key file path"): [NonEmpty],
+        new_path("Key file"): [ExistingFile],
+    }
+}
+
+/// Relink a passphrase key to a key file at a new location
+///
+/// For keys kept on removable media, or moved by hand. Fails if the file
+/// isn't a passphrase-protected key file.
+#[tauri::command]
+#[specta::specta]
+#[instrument(skip_all)]
+pub async fn relink_key_file(
+    request: RelinkKeyFileRequest,
+) -> CommandResponse<RelinkKeyFileResponse> {
+    request.validate()?;
+
+    let location = KeyRegistryService::new()
+        .relink_key_file(&request.key_id, Path::new(&request.new_path))
+        .map_err(|e| {
+            error!(key_id = %request.key_id, error = %e, "Failed to relink key file");
+            let (code, guidance) = match &e {
+                KeyManagementError::KeyNotFound(_) => {
+                    (ErrorCode::KeyNotFound, "Verify the key ID is correct")
+                }
+                KeyManagementError::InvalidOperation(_) => (
+                    ErrorCode::InvalidFileFormat,
+                    "Select the .agekey.enc file for this passphrase key",
+                ),
+                KeyManagementError::StorageError(_) => (
+                    ErrorCode::FileNotFound,
+                    "Check the drive is connected and the file is readable",
+                ),
+                _ => (ErrorCode::StorageFailed, "Check system logs or try again"),
+            };
+            Box::new(CommandError::operation(code, e.to_string()).with_recovery_guidance(guidance))
+        })?;
+
+    info!(
+        key_id = %request.key_id,
+        volume_label = ?location.volume_label(),
+        "Key file relinked"
+    );
+
+    Ok(RelinkKeyFileResponse {
+        key_id: request.key_id,
+        key_path: location.last_known_path.to_string_lossy().to_string(),
+        volume_label: location.volume_label().map(str::to_string),
+    })
+}
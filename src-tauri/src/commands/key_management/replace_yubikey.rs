@@ -0,0 +1,113 @@
+//! Lost YubiKey Replacement Command
+//!
+//! Guided replacement of a lost YubiKey: the replacement must be connected
+//! and registered, each selected vault's latest archive is re-encrypted
+//! without the lost key, and the lost key is revoked. Vaults that fail stay
+//! flagged in the report and can be retried.
+
+use crate::commands::crypto::update_global_progress;
+use crate::commands::validation::{NonEmpty, NonEmptyId, input_rules, invalid};
+use crate::services::key_management::shared::application::services::UnlockKey;
+use crate::services::key_management::shared::domain::models::{
+    VaultSelection, YubiKeyReplacementReport,
+};
+use crate::services::key_management::shared::{KeyManagementError, KeyManager};
+use crate::services::shared::infrastructure::{OperationKind, ProgressManager, begin_operation};
+use crate::types::{CommandError, CommandResponse, ErrorCode, ValidateInput, ValidationRule};
+use age::secrecy::SecretString;
+use serde::Deserialize;
+use tracing::{error, info, instrument};
+
+const PROGRESS_TOTAL_WORK: u64 = 100;
+
+/// Request to replace a lost YubiKey
+#[derive(Debug, Deserialize, specta::Type)]
+pub struct ReplaceYubiKeyRequest {
+    pub lost_key_id: String,
+    pub vaults: VaultSelection,
+    /// Another key attached to the vaults, used to open their archives
+    pub unlock_key_id: String,
+    /// Passphrase, or PIN when the unlock key is a YubiKey
+    pub unlock_secret: String,
+}
+
+fn require_vault(request: &ReplaceYubiKeyRequest) -> Result<(), Box<CommandError>> {
+    if let VaultSelection::Selected(vault_ids) = &request.vaults
+        && vault_ids.is_empty()
+    {
+        return Err(Box::new(invalid(
+            "vaults",
+            ValidationRule::NonEmptyList,
+            "Select at least one vault",
+        )));
+    }
+    Ok(())
+}
+
+input_rules! {
+    ReplaceYubiKeyRequest {
+        lost_key_id("Lost key ID"): [NonEmptyId],
+        unlock_key_id("Unlock key ID"): [NonEmptyId],
+        unlock_secret("Passphrase or PIN"): [NonEmpty],
+    }
+    then require_vault
+}
+
+/// Replace a lost YubiKey with the connected replacement
+///
+/// Reports per vault whether its latest archive was re-encrypted; vaults
+/// that failed still list the lost key and can be retried.
+#[tauri::command]
+#[specta::specta]
+#[instrument(skip(request), fields(lost_key_id = %request.lost_key_id))]
+pub async fn replace_yubikey(
+    request: ReplaceYubiKeyRequest,
+) -> CommandResponse<YubiKeyReplacementReport> {
+    request.validate()?;
+    let _operation = begin_operation(OperationKind::KeyReplacement)
+        .map_err(|e| Box::new(CommandError::from(e)))?;
+
+    let operation_id = format!("replace_yubikey_{}", chrono::Utc::now().timestamp());
+    let mut progress =
+        ProgressManager::new(operation_id, PROGRESS_TOTAL_WORK).with_callback(Box::new(|update| {
+            update_global_progress(&update.operation_id.clone(), update)
+        }));
+    let unlock = UnlockKey {
+        key_id: request.unlock_key_id,
+        secret: SecretString::from(request.unlock_secret),
+    };
+
+    let report = KeyManager::new()
+        .replace_yubikey(
+            &request.lost_key_id,
+            &request.vaults,
+            &unlock,
+            &mut progress,
+        )
+        .await
+        .map_err(|e| {
+            error!(error = %e, "YubiKey replacement failed");
+            let (code, guidance) = match &e {
+                KeyManagementError::KeyNotFound(_) => {
+                    (ErrorCode::KeyNotFound, "Verify the key ID is correct")
+                }
+                KeyManagementError::ReplacementNotRegistered(_) => (
+                    ErrorCode::YubiKeyInitializationFailed,
+                    "Register the connected YubiKey, then start the replacement again",
+                ),
+                KeyManagementError::InvalidOperation(_) => (
+                    ErrorCode::InvalidInput,
+                    "Connect only the replacement YubiKey and unlock with another key",
+                ),
+                _ => (ErrorCode::StorageFailed, "Check system logs or try again"),
+            };
+            Box::new(CommandError::operation(code, e.to_string()).with_recovery_guidance(guidance))
+        })?;
+
+    info!(
+        replacement_key_id = %report.replacement_key_id,
+        exposed = report.exposed_vault_ids().len(),
+        "YubiKey replacement finished"
+    );
+    Ok(report)
+}
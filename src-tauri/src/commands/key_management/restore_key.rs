@@ -2,11 +2,14 @@
 //!
 //! Commands for restoring deactivated keys within the 30-day grace period
 
+use crate::commands::validation::{NonEmptyId, input_rules};
+use crate::logging::current_trace_id;
 use crate::services::key_management::shared::infrastructure::KeyRegistry;
-use crate::types::{CommandError, CommandResponse, ErrorCode};
+use crate::services::shared::infrastructure::{ChangeEvent, publish};
+use crate::types::{CommandError, CommandResponse, ErrorCode, ValidateInput};
 use chrono::Utc;
 use serde::{Deserialize, Serialize};
-use tracing::{debug, error, info};
+use tracing::{debug, error, info, instrument};
 
 /// Request to restore a deactivated key
 #[derive(Debug, Clone, Serialize, Deserialize, specta::Type)]
@@ -15,6 +18,12 @@ pub struct RestoreKeyRequest {
     pub key_id: String,
 }
 
+input_rules! {
+    RestoreKeyRequest {
+        key_id("Key ID"): [NonEmptyId],
+    }
+}
+
 /// Response from key restoration
 #[derive(Debug, Clone, Serialize, Deserialize, specta::Type)]
 pub struct RestoreKeyResponse {
@@ -34,6 +43,7 @@ pub struct RestoreKeyResponse {
 /// This operation is NOT idempotent - attempting to restore a non-deactivated key returns an error.
 #[tauri::command]
 #[specta::specta]
+#[instrument(skip_all)]
 pub async fn restore_key(request: RestoreKeyRequest) -> CommandResponse<RestoreKeyResponse> {
     debug!(
         key_id = %request.key_id,
@@ -41,17 +51,7 @@ pub async fn restore_key(request: RestoreKeyRequest) -> CommandResponse<RestoreK
     );
 
     // Validate input
-    if request.key_id.is_empty() {
-        return Err(Box::new(CommandError {
-            code: ErrorCode::InvalidInput,
-            message: "Key ID cannot be empty".to_string(),
-            details: None,
-            recovery_guidance: Some("Provide a valid key ID".to_string()),
-            user_actionable: true,
-            trace_id: None,
-            span_id: None,
-        }));
-    }
+    request.validate()?;
 
     // Load registry
     let mut registry = KeyRegistry::load().map_err(|e| {
@@ -62,8 +62,10 @@ pub async fn restore_key(request: RestoreKeyRequest) -> CommandResponse<RestoreK
             details: Some(e.to_string()),
             recovery_guidance: Some("Check system logs or try again".to_string()),
             user_actionable: false,
-            trace_id: None,
+            trace_id: current_trace_id(),
             span_id: None,
+            validation: None,
+            help_available: true,
         })
     })?;
 
@@ -75,8 +77,10 @@ pub async fn restore_key(request: RestoreKeyRequest) -> CommandResponse<RestoreK
             details: None,
             recovery_guidance: Some("Verify the key ID is correct".to_string()),
             user_actionable: true,
-            trace_id: None,
+            trace_id: current_trace_id(),
             span_id: None,
+            validation: None,
+            help_available: true,
         })
     })?;
 
@@ -97,8 +101,10 @@ pub async fn restore_key(request: RestoreKeyRequest) -> CommandResponse<RestoreK
             details: None,
             recovery_guidance: Some("Only deactivated keys can be restored".to_string()),
             user_actionable: true,
-            trace_id: None,
+            trace_id: current_trace_id(),
             span_id: None,
+            validation: None,
+            help_available: true,
         }));
     }
 
@@ -119,8 +125,10 @@ pub async fn restore_key(request: RestoreKeyRequest) -> CommandResponse<RestoreK
                     "The key may be in an invalid state for restoration".to_string(),
                 ),
                 user_actionable: true,
-                trace_id: None,
+                trace_id: current_trace_id(),
                 span_id: None,
+                validation: None,
+                help_available: true,
             })
         })?;
 
@@ -137,11 +145,18 @@ pub async fn restore_key(request: RestoreKeyRequest) -> CommandResponse<RestoreK
             details: Some(e.to_string()),
             recovery_guidance: Some("Try again or check system logs".to_string()),
             user_actionable: false,
-            trace_id: None,
+            trace_id: current_trace_id(),
             span_id: None,
+            validation: None,
+            help_available: true,
         })
     })?;
 
+    publish(ChangeEvent::KeyRestored {
+        key_id: request.key_id.clone(),
+        status: new_status,
+    });
+
     info!(
         key_id = %request.key_id,
         new_status = ?new_status,
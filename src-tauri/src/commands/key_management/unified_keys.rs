@@ -9,12 +9,17 @@
 //! - Future-proof for new key types (HSM, Smart Cards, etc.)
 //! - Simplified frontend integration with unified data structures
 
-use crate::commands::command_types::{CommandError, ErrorCode};
+use crate::commands::command_types::{CommandError, ErrorCode, ValidateInput, ValidationRule};
+use crate::commands::listing::{Listable, PageRequest, SortSpec, compare_text, sort_and_paginate};
+use crate::commands::validation::{ExistingVaultId, NonEmpty, NonEmptyId, input_rules, invalid};
+use crate::logging::current_trace_id;
 use crate::prelude::*;
 use crate::services::key_management::shared::domain::models::key_lifecycle::KeyLifecycleStatus;
-use crate::services::key_management::shared::{KeyEntry, KeyManager};
+use crate::services::key_management::shared::{KeyEntry, KeyManagementError, KeyManager};
 use crate::services::vault;
+use crate::services::vault::domain::{NameKind, NameValidator, VaultError};
 use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
 
 // Re-export domain types for commands
 pub use crate::services::key_management::passphrase::domain::models::passphrase_key_info::PassphraseKeyInfo;
@@ -137,22 +142,69 @@ pub fn convert_available_yubikey_to_unified(
     }
 }
 
+/// Fields `list_unified_keys` can sort by
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+#[serde(rename_all = "snake_case")]
+pub enum KeySortField {
+    Label,
+    CreatedAt,
+    LastUsed,
+}
+
+impl Listable for GlobalKey {
+    type SortField = KeySortField;
+
+    fn list_id(&self) -> &str {
+        &self.id
+    }
+
+    fn compare_by(&self, other: &Self, field: KeySortField) -> Ordering {
+        match field {
+            KeySortField::Label => compare_text(&self.label, &other.label),
+            KeySortField::CreatedAt => self.created_at.cmp(&other.created_at),
+            // Never-used keys sort first
+            KeySortField::LastUsed => self.last_used.cmp(&other.last_used),
+        }
+    }
+}
+
+/// One page of keys
+#[derive(Debug, Serialize, specta::Type)]
+pub struct ListUnifiedKeysResponse {
+    /// Sorted by label unless asked otherwise
+    pub keys: Vec<GlobalKey>,
+    /// Keys matching the filter, in all pages
+    pub total_count: usize,
+}
+
 /// List keys with flexible filtering options - unified API
 #[tauri::command]
 #[specta::specta]
-pub async fn list_unified_keys(filter: KeyListFilter) -> Result<Vec<GlobalKey>, CommandError> {
+#[instrument(skip_all)]
+pub async fn list_unified_keys(
+    filter: KeyListFilter,
+    sort: Option<SortSpec<KeySortField>>,
+    page: Option<PageRequest>,
+) -> Result<ListUnifiedKeysResponse, CommandError> {
     info!("Listing keys with filter: {:?}", filter);
 
     let manager = KeyManager::new();
-    manager
+    let keys = manager
         .list_keys(filter)
         .await
-        .map_err(|e| CommandError::operation(ErrorCode::InternalError, e.to_string()))
+        .map_err(|e| CommandError::operation(ErrorCode::InternalError, e.to_string()))?;
+
+    let page = sort_and_paginate(keys, sort, KeySortField::Label, page);
+    Ok(ListUnifiedKeysResponse {
+        keys: page.items,
+        total_count: page.total_count,
+    })
 }
 
 /// Simple test command to verify the unified API works
 #[tauri::command]
 #[specta::specta]
+#[instrument(skip_all)]
 pub async fn test_unified_keys() -> Result<String, CommandError> {
     Ok("Unified key API is working!".to_string())
 }
@@ -165,6 +217,12 @@ pub struct GetVaultKeysRequest {
     pub include_all: Option<bool>,
 }
 
+input_rules! {
+    GetVaultKeysRequest {
+        vault_id("Vault ID"): [ExistingVaultId],
+    }
+}
+
 /// Response containing vault keys
 #[derive(Debug, Serialize, specta::Type)]
 pub struct GetVaultKeysResponse {
@@ -178,9 +236,13 @@ pub struct GetVaultKeysResponse {
 #[instrument(skip_all, fields(vault_id = %input.vault_id))]
 pub async fn get_vault_keys(input: GetVaultKeysRequest) -> CommandResponse<GetVaultKeysResponse> {
     debug!(vault_id = %input.vault_id, "get_vault_keys called");
+    input.validate()?;
 
-    // Delegate to unified API for actual implementation
-    match list_unified_keys(KeyListFilter::ForVault(input.vault_id.clone())).await {
+    // Same keys as the unified API, unpaged: a vault holds only a few
+    match KeyManager::new()
+        .list_keys(KeyListFilter::ForVault(input.vault_id.clone()))
+        .await
+    {
         Ok(unified_keys) => {
             // Convert from unified GlobalKey to vault VaultKey
             let key_refs: Vec<crate::services::key_management::shared::domain::models::VaultKey> = unified_keys
@@ -201,6 +263,12 @@ pub async fn get_vault_keys(input: GetVaultKeysRequest) -> CommandResponse<GetVa
                         KeyType::Recipient => {
                             crate::services::key_management::shared::domain::models::KeyType::Recipient
                         }
+                        KeyType::YubiKeyPending => {
+                            crate::services::key_management::shared::domain::models::KeyType::YubiKeyPending
+                        }
+                        KeyType::Contact { fingerprint } => {
+                            crate::services::key_management::shared::domain::models::KeyType::Contact { fingerprint }
+                        }
                     },
                     label: key_info.label,
                     lifecycle_status: key_info.lifecycle_status,
@@ -212,7 +280,7 @@ pub async fn get_vault_keys(input: GetVaultKeysRequest) -> CommandResponse<GetVa
             info!(
                 vault_id = %input.vault_id,
                 keys_count = key_refs.len(),
-                "Returning vault keys from unified API"
+                "Returning vault keys"
             );
             Ok(GetVaultKeysResponse {
                 vault_id: input.vault_id,
@@ -220,15 +288,17 @@ pub async fn get_vault_keys(input: GetVaultKeysRequest) -> CommandResponse<GetVa
             })
         }
         Err(e) => {
-            error!(vault_id = %input.vault_id, error = ?e, "Failed to get vault keys");
+            error!(vault_id = %input.vault_id, error = %e, "Failed to get vault keys");
             Err(Box::new(CommandError {
                 code: ErrorCode::VaultNotFound,
-                message: format!("Failed to get vault keys: {:?}", e),
+                message: format!("Failed to get vault keys: {}", e),
                 details: None,
                 recovery_guidance: Some("Check vault ID and try again".to_string()),
                 user_actionable: true,
-                trace_id: None,
+                trace_id: current_trace_id(),
                 span_id: None,
+                validation: None,
+                help_available: true,
             }))
         }
     }
@@ -243,6 +313,13 @@ pub struct RemoveKeyFromVaultRequest {
     pub key_id: String,
 }
 
+input_rules! {
+    RemoveKeyFromVaultRequest {
+        vault_id("Vault ID"): [ExistingVaultId],
+        key_id("Key ID"): [NonEmptyId],
+    }
+}
+
 /// Response from removing key
 #[derive(Debug, Serialize, specta::Type)]
 pub struct RemoveKeyFromVaultResponse {
@@ -256,6 +333,8 @@ pub struct RemoveKeyFromVaultResponse {
 pub async fn remove_key_from_vault(
     input: RemoveKeyFromVaultRequest,
 ) -> CommandResponse<RemoveKeyFromVaultResponse> {
+    input.validate()?;
+
     info!(
         vault_id = %input.vault_id,
         key_id = %input.key_id,
@@ -281,8 +360,10 @@ pub async fn remove_key_from_vault(
                 details: None,
                 recovery_guidance: Some("Check vault and key IDs".to_string()),
                 user_actionable: true,
-                trace_id: None,
+                trace_id: current_trace_id(),
                 span_id: None,
+                validation: None,
+                help_available: true,
             })
         })
 }
@@ -295,6 +376,14 @@ pub struct UpdateKeyLabelRequest {
     pub new_label: String,
 }
 
+input_rules! {
+    UpdateKeyLabelRequest {
+        vault_id("Vault ID"): [ExistingVaultId],
+        key_id("Key ID"): [NonEmptyId],
+        new_label("Key label"): [NonEmpty],
+    }
+}
+
 /// Response from updating key label
 #[derive(Debug, Serialize, specta::Type)]
 pub struct UpdateKeyLabelResponse {
@@ -308,28 +397,17 @@ pub struct UpdateKeyLabelResponse {
 pub async fn update_key_label(
     input: UpdateKeyLabelRequest,
 ) -> CommandResponse<UpdateKeyLabelResponse> {
-    // Validate input
-    if input.new_label.trim().is_empty() {
-        return Err(Box::new(CommandError {
-            code: ErrorCode::InvalidInput,
-            message: "New label cannot be empty".to_string(),
-            details: None,
-            recovery_guidance: Some("Provide a valid label".to_string()),
-            user_actionable: true,
-            trace_id: None,
-            span_id: None,
-        }));
-    }
+    input.validate()?;
+    let manager = KeyManager::new();
+    let new_label = validate_new_label(&manager, &input.key_id, &input.new_label)?;
 
     info!(
         vault_id = %input.vault_id,
         key_id = %input.key_id,
-        new_label = %input.new_label,
+        new_label = %new_label,
         "Updating key label"
     );
 
-    let manager = KeyManager::new();
-
     // Get existing key entry
     let mut entry = manager.get_key(&input.key_id).map_err(|e| {
         Box::new(CommandError {
@@ -338,37 +416,29 @@ pub async fn update_key_label(
             details: None,
             recovery_guidance: Some("Check key ID".to_string()),
             user_actionable: true,
-            trace_id: None,
+            trace_id: current_trace_id(),
             span_id: None,
+            validation: None,
+            help_available: true,
         })
     })?;
 
     // Update label based on key type
     match &mut entry {
         KeyEntry::Passphrase { label, .. } => {
-            *label = input.new_label.trim().to_string();
+            *label = new_label;
         }
         KeyEntry::Yubikey { label, .. } => {
-            *label = input.new_label.trim().to_string();
+            *label = new_label;
         }
         KeyEntry::Recipient { label, .. } => {
-            *label = input.new_label.trim().to_string();
+            *label = new_label;
+        }
+        KeyEntry::YubikeyPending { label, .. } => {
+            *label = new_label;
         }
     }
 
-    // Save updated entry
-    manager.update_key(&input.key_id, entry).map_err(|e| {
-        Box::new(CommandError {
-            code: ErrorCode::InternalError,
-            message: format!("Failed to update key label: {}", e),
-            details: None,
-            recovery_guidance: Some("Try again or check system logs".to_string()),
-            user_actionable: true,
-            trace_id: None,
-            span_id: None,
-        })
-    })?;
-
     // Update vault metadata version
     let mut metadata = vault::load_vault(&input.vault_id).await.map_err(|e| {
         Box::new(CommandError {
@@ -377,8 +447,10 @@ pub async fn update_key_label(
             details: None,
             recovery_guidance: Some("Check vault ID".to_string()),
             user_actionable: true,
-            trace_id: None,
+            trace_id: current_trace_id(),
             span_id: None,
+            validation: None,
+            help_available: true,
         })
     })?;
 
@@ -391,23 +463,105 @@ pub async fn update_key_label(
             details: None,
             recovery_guidance: None,
             user_actionable: false,
-            trace_id: None,
+            trace_id: current_trace_id(),
             span_id: None,
+            validation: None,
+            help_available: true,
         })
     })?;
 
     metadata.increment_version(&device_info);
-    vault::save_vault(&metadata).await.map_err(|e| {
+
+    // Save registry entry and manifest version together
+    manager
+        .update_key_with_manifest(&input.key_id, entry, &metadata)
+        .map_err(|e| match e {
+            KeyManagementError::LabelConflict {
+                label,
+                existing_key_id,
+            } => Box::new(CommandError {
+                code: ErrorCode::KeyAlreadyExists,
+                message: format!("A key labelled '{}' already exists", label),
+                details: Some(existing_key_id),
+                recovery_guidance: Some("Choose a label that no other key uses".to_string()),
+                user_actionable: true,
+                trace_id: current_trace_id(),
+                span_id: None,
+                validation: None,
+                help_available: true,
+            }),
+            e => Box::new(CommandError {
+                code: ErrorCode::InternalError,
+                message: format!("Failed to update key label: {}", e),
+                details: None,
+                recovery_guidance: Some("Try again or check system logs".to_string()),
+                user_actionable: true,
+                trace_id: current_trace_id(),
+                span_id: None,
+                validation: None,
+                help_available: true,
+            }),
+        })?;
+
+    Ok(UpdateKeyLabelResponse { success: true })
+}
+
+/// Normalize a new label and reject ones confusable with another key's
+///
+/// Labels differing only in case are left to the registry's label conflict
+/// check, which names the key that already uses it.
+fn validate_new_label(
+    manager: &KeyManager,
+    key_id: &str,
+    new_label: &str,
+) -> Result<String, Box<CommandError>> {
+    let label = NameValidator::normalize(NameKind::KeyLabel, new_label).map_err(|e| {
+        Box::new(
+            invalid("new_label", ValidationRule::LabelFormat, e.to_string())
+                .with_recovery_guidance("Provide a valid label"),
+        )
+    })?;
+
+    let registry = manager.load_registry().map_err(|e| {
         Box::new(CommandError {
             code: ErrorCode::InternalError,
-            message: format!("Failed to save vault: {}", e),
+            message: format!("Failed to load key registry: {}", e),
             details: None,
             recovery_guidance: None,
             user_actionable: false,
-            trace_id: None,
+            trace_id: current_trace_id(),
+            span_id: None,
+            validation: None,
+            help_available: true,
+        })
+    })?;
+    let folded = label.to_lowercase();
+    let others = registry
+        .keys
+        .iter()
+        .filter(|(id, _)| id.as_str() != key_id)
+        .map(|(_, entry)| entry.label())
+        .filter(|other| other.trim().to_lowercase() != folded);
+
+    NameValidator::check_confusable(&label, others).map_err(|e| {
+        let details = match &e {
+            VaultError::NameConfusable { conflicts_with, .. } => Some(conflicts_with.clone()),
+            _ => None,
+        };
+        Box::new(CommandError {
+            code: ErrorCode::InvalidInput,
+            message: e.to_string(),
+            details,
+            recovery_guidance: Some(
+                "Choose a label that is clearly different from your other keys".to_string(),
+            ),
+            user_actionable: true,
+            trace_id: current_trace_id(),
             span_id: None,
+            validation: None,
+            help_available: true,
         })
     })?;
 
-    Ok(UpdateKeyLabelResponse { success: true })
+    Ok(label)
 }
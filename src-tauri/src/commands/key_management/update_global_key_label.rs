@@ -3,13 +3,15 @@
 //! Commands for updating key labels in the global registry (for unattached keys only)
 //! For unattached keys, performs full rename: label, key_id, filename, and disk file
 
+use crate::commands::validation::{LabelFormat, NonEmptyId, input_rules};
+use crate::logging::current_trace_id;
 use crate::services::key_management::shared::infrastructure::KeyRegistry;
 use crate::services::shared::infrastructure::path_management::get_keys_dir;
-use crate::services::shared::infrastructure::sanitize_label;
-use crate::types::{CommandError, CommandResponse, ErrorCode};
+use crate::services::shared::infrastructure::{ChangeEvent, publish_all, sanitize_label};
+use crate::types::{CommandError, CommandResponse, ErrorCode, ValidateInput};
 use serde::{Deserialize, Serialize};
 use std::fs;
-use tracing::{debug, error, info, warn};
+use tracing::{debug, error, info, instrument, warn};
 
 /// Request to update a key's label in the global registry
 #[derive(Debug, Clone, Serialize, Deserialize, specta::Type)]
@@ -20,6 +22,13 @@ pub struct UpdateGlobalKeyLabelRequest {
     pub new_label: String,
 }
 
+input_rules! {
+    UpdateGlobalKeyLabelRequest {
+        key_id("Key ID"): [NonEmptyId],
+        new_label("Key label"): [LabelFormat],
+    }
+}
+
 /// Response from global key label update
 #[derive(Debug, Clone, Serialize, Deserialize, specta::Type)]
 pub struct UpdateGlobalKeyLabelResponse {
@@ -53,6 +62,7 @@ pub struct UpdateGlobalKeyLabelResponse {
 /// - ANY attached key (vault_associations.length > 0)
 #[tauri::command]
 #[specta::specta]
+#[instrument(skip_all)]
 pub async fn update_global_key_label(
     request: UpdateGlobalKeyLabelRequest,
 ) -> CommandResponse<UpdateGlobalKeyLabelResponse> {
@@ -63,45 +73,8 @@ pub async fn update_global_key_label(
     );
 
     // Validate input
-    if request.key_id.is_empty() {
-        return Err(Box::new(CommandError {
-            code: ErrorCode::InvalidInput,
-            message: "Key ID cannot be empty".to_string(),
-            details: None,
-            recovery_guidance: Some("Provide a valid key ID".to_string()),
-            user_actionable: true,
-            trace_id: None,
-            span_id: None,
-        }));
-    }
-
+    request.validate()?;
     let trimmed_label = request.new_label.trim();
-    if trimmed_label.is_empty() {
-        return Err(Box::new(CommandError {
-            code: ErrorCode::InvalidInput,
-            message: "New label cannot be empty".to_string(),
-            details: None,
-            recovery_guidance: Some("Provide a valid label (1-24 characters)".to_string()),
-            user_actionable: true,
-            trace_id: None,
-            span_id: None,
-        }));
-    }
-
-    if trimmed_label.len() > 24 {
-        return Err(Box::new(CommandError {
-            code: ErrorCode::InvalidInput,
-            message: format!(
-                "Label is too long ({} characters, maximum 24)",
-                trimmed_label.len()
-            ),
-            details: None,
-            recovery_guidance: Some("Use a shorter label (up to 24 characters)".to_string()),
-            user_actionable: true,
-            trace_id: None,
-            span_id: None,
-        }));
-    }
 
     // Load registry
     let mut registry = KeyRegistry::load().map_err(|e| {
@@ -112,8 +85,10 @@ pub async fn update_global_key_label(
             details: Some(e.to_string()),
             recovery_guidance: Some("Check system logs or try again".to_string()),
             user_actionable: false,
-            trace_id: None,
+            trace_id: current_trace_id(),
             span_id: None,
+            validation: None,
+            help_available: true,
         })
     })?;
 
@@ -127,8 +102,10 @@ pub async fn update_global_key_label(
                 details: None,
                 recovery_guidance: Some("Verify the key ID is correct".to_string()),
                 user_actionable: true,
-                trace_id: None,
+                trace_id: current_trace_id(),
                 span_id: None,
+                validation: None,
+                help_available: true,
             })
         })?
         .clone();
@@ -158,8 +135,10 @@ pub async fn update_global_key_label(
                     .to_string(),
             ),
             user_actionable: true,
-            trace_id: None,
+            trace_id: current_trace_id(),
             span_id: None,
+            validation: None,
+            help_available: true,
         }));
     }
 
@@ -178,6 +157,27 @@ pub async fn update_global_key_label(
         });
     }
 
+    // Labels must stay unique across the registry
+    if let Err(conflict) = registry.check_label_change(&request.key_id, trimmed_label) {
+        error!(
+            key_id = %request.key_id,
+            label = %conflict.label,
+            existing_key_id = %conflict.existing_key_id,
+            "Rename blocked: label already in use"
+        );
+        return Err(Box::new(CommandError {
+            code: ErrorCode::KeyAlreadyExists,
+            message: format!("A key labelled '{}' already exists", conflict.label),
+            details: Some(conflict.existing_key_id),
+            recovery_guidance: Some("Choose a label that no other key uses".to_string()),
+            user_actionable: true,
+            trace_id: current_trace_id(),
+            span_id: None,
+            validation: None,
+            help_available: true,
+        }));
+    }
+
     // FULL RENAME: Sanitize new label to derive new key_id and filename
     // This matches Create/Import pattern where all components derive from label
     let sanitized = sanitize_label(trimmed_label).map_err(|e| {
@@ -189,8 +189,10 @@ pub async fn update_global_key_label(
                 "Use only alphanumeric characters, spaces, hyphens, and underscores".to_string(),
             ),
             user_actionable: true,
-            trace_id: None,
+            trace_id: current_trace_id(),
             span_id: None,
+            validation: None,
+            help_available: true,
         })
     })?;
 
@@ -215,8 +217,10 @@ pub async fn update_global_key_label(
                 "Choose a different label that doesn't conflict with existing keys".to_string(),
             ),
             user_actionable: true,
-            trace_id: None,
+            trace_id: current_trace_id(),
             span_id: None,
+            validation: None,
+            help_available: true,
         }));
     }
 
@@ -233,8 +237,10 @@ pub async fn update_global_key_label(
                     details: Some(e.to_string()),
                     recovery_guidance: None,
                     user_actionable: false,
-                    trace_id: None,
+                    trace_id: current_trace_id(),
                     span_id: None,
+                    validation: None,
+                    help_available: true,
                 })
             })?;
 
@@ -259,8 +265,10 @@ pub async fn update_global_key_label(
                             .to_string(),
                     ),
                     user_actionable: true,
-                    trace_id: None,
+                    trace_id: current_trace_id(),
                     span_id: None,
+                    validation: None,
+                    help_available: true,
                 }));
             }
 
@@ -324,6 +332,17 @@ pub async fn update_global_key_label(
                 "Updated Recipient entry label"
             );
         }
+        crate::services::key_management::shared::infrastructure::KeyEntry::YubikeyPending {
+            label,
+            ..
+        } => {
+            *label = trimmed_label.to_string();
+            debug!(
+                old_label = %current_label,
+                new_label = %trimmed_label,
+                "Updated reserved YubiKey slot label"
+            );
+        }
     }
 
     // Remove old entry and insert with new key_id
@@ -339,8 +358,10 @@ pub async fn update_global_key_label(
             details: Some(e),
             recovery_guidance: Some("Try again or check system logs".to_string()),
             user_actionable: false,
-            trace_id: None,
+            trace_id: current_trace_id(),
             span_id: None,
+            validation: None,
+            help_available: true,
         })
     })?;
 
@@ -358,8 +379,10 @@ pub async fn update_global_key_label(
                 details: Some(e),
                 recovery_guidance: Some("Try again or check system logs".to_string()),
                 user_actionable: false,
-                trace_id: None,
+                trace_id: current_trace_id(),
                 span_id: None,
+                validation: None,
+                help_available: true,
             })
         })?;
 
@@ -372,11 +395,31 @@ pub async fn update_global_key_label(
             details: Some(e.to_string()),
             recovery_guidance: Some("Try again or check system logs".to_string()),
             user_actionable: false,
-            trace_id: None,
+            trace_id: current_trace_id(),
             span_id: None,
+            validation: None,
+            help_available: true,
         })
     })?;
 
+    // The key ID follows the label, so a new ID reaches the UI as a replaced entry
+    if new_key_id == old_key_id {
+        publish_all(vec![ChangeEvent::KeyRenamed {
+            key_id: new_key_id.clone(),
+            label: trimmed_label.to_string(),
+        }]);
+    } else {
+        publish_all(vec![
+            ChangeEvent::KeyRemoved {
+                key_id: old_key_id.clone(),
+            },
+            ChangeEvent::KeyAdded {
+                key_id: new_key_id.clone(),
+                label: trimmed_label.to_string(),
+            },
+        ]);
+    }
+
     info!(
         old_key_id = %old_key_id,
         new_key_id = %new_key_id,
@@ -396,8 +439,10 @@ pub async fn update_global_key_label(
                 details: Some(e.to_string()),
                 recovery_guidance: None,
                 user_actionable: false,
-                trace_id: None,
+                trace_id: current_trace_id(),
                 span_id: None,
+                validation: None,
+                help_available: true,
             })
         })?;
 
@@ -11,6 +11,7 @@
 use crate::services::key_management::yubikey::domain::models::UnlockMethod;
 use serde::Deserialize;
 use tauri;
+use tracing::instrument;
 
 // Define types that were previously imported from deleted modules
 #[derive(Debug, Deserialize, serde::Serialize, specta::Type)]
@@ -53,6 +54,7 @@ pub struct YubiKeyCredentials {
 /// Currently uses existing implementation - will be migrated to YubiKeyManager in next iteration
 #[tauri::command]
 #[specta::specta]
+#[instrument(skip_all)]
 pub async fn yubikey_decrypt_file(
     _encrypted_file: String,
     _unlock_method: Option<UnlockMethod>,
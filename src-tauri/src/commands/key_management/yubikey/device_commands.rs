@@ -10,6 +10,8 @@
 //! - register_yubikey: Register existing YubiKey device
 
 use crate::commands::command_types::{CommandError, ErrorCode};
+use crate::logging::current_trace_id;
+use crate::logging::spawn_blocking;
 use crate::prelude::*;
 use crate::services::key_management::yubikey::{
     YubiKeyManager,
@@ -37,6 +39,7 @@ pub struct StreamlinedYubiKeyInitResult {
 /// Uses YubiKeyManager for centralized device and registry operations
 #[tauri::command]
 #[specta::specta]
+#[instrument(skip_all)]
 pub async fn list_yubikeys() -> Result<Vec<YubiKeyStateInfo>, CommandError> {
     info!("Listing YubiKeys with state detection");
 
@@ -62,6 +65,7 @@ pub async fn list_yubikeys() -> Result<Vec<YubiKeyStateInfo>, CommandError> {
 /// Uses YubiKeyManager for complete hardware and software initialization
 #[tauri::command]
 #[specta::specta]
+#[instrument(skip_all)]
 pub async fn init_yubikey(
     serial: String,
     new_pin: String,
@@ -74,7 +78,7 @@ pub async fn init_yubikey(
 
     // Helper to update progress and emit events (follows encryption/decryption pattern)
     let update_progress = |phase: crate::types::YubiKeyPhase, message: &str| {
-        use crate::types::{ProgressDetails, ProgressUpdate, YubiKeyOperationType};
+        use crate::types::{IoPriority, ProgressDetails, ProgressUpdate, YubiKeyOperationType};
         let progress_update = ProgressUpdate {
             operation_id: operation_id.clone(),
             progress: match &phase {
@@ -93,6 +97,8 @@ pub async fn init_yubikey(
             }),
             timestamp: chrono::Utc::now(),
             estimated_time_remaining: None,
+            io_priority: IoPriority::Normal,
+            trace_id: current_trace_id(),
         };
 
         // Store in global HashMap
@@ -227,6 +233,7 @@ pub async fn init_yubikey(
 /// - register_yubikey: For ORPHANED YubiKeys (reads existing identity)
 #[tauri::command]
 #[specta::specta]
+#[instrument(skip_all)]
 pub async fn register_yubikey(
     serial: String,
     label: String,
@@ -257,7 +264,7 @@ pub async fn register_yubikey(
 
     // Validate device exists
     let device = manager
-        .detect_device(&serial_obj)
+        .detect_device(&serial_obj, None)
         .await
         .map_err(|e| {
             CommandError::operation(
@@ -393,12 +400,16 @@ pub async fn register_yubikey(
 /// 1. Change management key to TDES+protected
 /// 2. Generate age identity (requires touch)
 /// 3. Register in global registry
+///
+/// With one PIN attempt left the PIN is only sent if `accept_last_attempt` is set.
 #[tauri::command]
 #[specta::specta]
+#[instrument(skip_all)]
 pub async fn complete_yubikey_setup(
     serial: String,
     pin: String,
     label: String,
+    accept_last_attempt: Option<bool>,
     window: tauri::Window,
 ) -> Result<StreamlinedYubiKeyInitResult, CommandError> {
     // Generate operation ID for progress tracking
@@ -406,7 +417,7 @@ pub async fn complete_yubikey_setup(
 
     // Helper to update progress and emit events
     let update_progress = |phase: crate::types::YubiKeyPhase, message: &str| {
-        use crate::types::{ProgressDetails, ProgressUpdate, YubiKeyOperationType};
+        use crate::types::{IoPriority, ProgressDetails, ProgressUpdate, YubiKeyOperationType};
         let progress_update = ProgressUpdate {
             operation_id: operation_id.clone(),
             progress: match &phase {
@@ -425,6 +436,8 @@ pub async fn complete_yubikey_setup(
             }),
             timestamp: chrono::Utc::now(),
             estimated_time_remaining: None,
+            io_priority: IoPriority::Normal,
+            trace_id: current_trace_id(),
         };
 
         // Store in global HashMap
@@ -462,7 +475,7 @@ pub async fn complete_yubikey_setup(
 
     // Validate device exists
     let device = manager
-        .detect_device(&serial_obj)
+        .detect_device(&serial_obj, None)
         .await
         .map_err(|e| {
             CommandError::operation(
@@ -486,39 +499,51 @@ pub async fn complete_yubikey_setup(
         ));
     }
 
-    // Step 1: Change management key to TDES+protected
-    use crate::services::key_management::yubikey::infrastructure::pty::ykman_ops::piv_operations::change_management_key_pty;
-
-    tokio::task::spawn_blocking({
-        let serial_clone = serial_obj.value().to_string();
-        let pin_clone = pin_obj.value().to_string();
-        move || change_management_key_pty(&serial_clone, &pin_clone)
-    })
-    .await
-    .map_err(|e| CommandError::operation(ErrorCode::InternalError, format!("Task error: {e}")))?
-    .map_err(|e| {
-        CommandError::operation(
-            ErrorCode::YubiKeyInitializationFailed,
-            format!("Failed to change management key: {e}"),
-        )
-    })?;
-
-    // Report waiting for touch (mgmt key done, age key generation next)
-    update_progress(
-        crate::types::YubiKeyPhase::WaitingForTouch,
-        "Management key updated. Touch your YubiKey to generate encryption key",
-    );
-
-    // Step 2: Generate age identity (requires touch)
+    // Steps 1 and 2 both spend the PIN, so they run under the PIN guard
     let identity = manager
-        .generate_identity(&serial_obj, &pin_obj, 1)
-        .await
-        .map_err(|e| {
-            CommandError::operation(
-                ErrorCode::YubiKeyInitializationFailed,
-                format!("Failed to generate identity: {e}"),
-            )
-        })?;
+        .guard_pin_attempt(
+            &serial_obj,
+            &pin_obj,
+            accept_last_attempt.unwrap_or(false),
+            async {
+                // Step 1: Change management key to TDES+protected
+                use crate::services::key_management::yubikey::infrastructure::pty::ykman_ops::piv_operations::change_management_key_pty;
+
+                spawn_blocking({
+                    let serial_clone = serial_obj.value().to_string();
+                    let pin_clone = pin_obj.value().to_string();
+                    move || change_management_key_pty(&serial_clone, &pin_clone)
+                })
+                .await
+                .map_err(|e| {
+                    CommandError::operation(ErrorCode::InternalError, format!("Task error: {e}"))
+                })?
+                .map_err(|e| {
+                    CommandError::operation(
+                        ErrorCode::YubiKeyInitializationFailed,
+                        format!("Failed to change management key: {e}"),
+                    )
+                })?;
+
+                // Report waiting for touch (mgmt key done, age key generation next)
+                update_progress(
+                    crate::types::YubiKeyPhase::WaitingForTouch,
+                    "Management key updated. Touch your YubiKey to generate encryption key",
+                );
+
+                // Step 2: Generate age identity (requires touch)
+                manager
+                    .generate_identity(&serial_obj, &pin_obj, 1)
+                    .await
+                    .map_err(|e| {
+                        CommandError::operation(
+                            ErrorCode::YubiKeyInitializationFailed,
+                            format!("Failed to generate identity: {e}"),
+                        )
+                    })
+            },
+        )
+        .await?;
 
     // Step 3: Register in global registry
     let recovery_placeholder = String::new(); // Not used for reused keys
@@ -572,12 +597,16 @@ pub async fn complete_yubikey_setup(
 /// but no age identity yet. This command only:
 /// 1. Generates age identity (requires touch)
 /// 2. Registers in global registry
+///
+/// With one PIN attempt left the PIN is only sent if `accept_last_attempt` is set.
 #[tauri::command]
 #[specta::specta]
+#[instrument(skip_all)]
 pub async fn generate_yubikey_identity(
     serial: String,
     pin: String,
     label: String,
+    accept_last_attempt: Option<bool>,
     window: tauri::Window,
 ) -> Result<StreamlinedYubiKeyInitResult, CommandError> {
     // Generate operation ID for progress tracking
@@ -585,7 +614,7 @@ pub async fn generate_yubikey_identity(
 
     // Helper to update progress and emit events
     let update_progress = |phase: crate::types::YubiKeyPhase, message: &str| {
-        use crate::types::{ProgressDetails, ProgressUpdate, YubiKeyOperationType};
+        use crate::types::{IoPriority, ProgressDetails, ProgressUpdate, YubiKeyOperationType};
         let progress_update = ProgressUpdate {
             operation_id: operation_id.clone(),
             progress: match &phase {
@@ -603,6 +632,8 @@ pub async fn generate_yubikey_identity(
             }),
             timestamp: chrono::Utc::now(),
             estimated_time_remaining: None,
+            io_priority: IoPriority::Normal,
+            trace_id: current_trace_id(),
         };
 
         // Store in global HashMap
@@ -640,7 +671,7 @@ pub async fn generate_yubikey_identity(
 
     // Validate device exists
     let device = manager
-        .detect_device(&serial_obj)
+        .detect_device(&serial_obj, None)
         .await
         .map_err(|e| {
             CommandError::operation(
@@ -666,9 +697,17 @@ pub async fn generate_yubikey_identity(
 
     // Generate age identity (requires touch)
     let identity = manager
-        .generate_identity(&serial_obj, &pin_obj, 1)
+        .guard_pin_attempt(
+            &serial_obj,
+            &pin_obj,
+            accept_last_attempt.unwrap_or(false),
+            manager.generate_identity(&serial_obj, &pin_obj, 1),
+        )
         .await
         .map_err(|e| {
+            if e.is_pin_guard() {
+                return CommandError::from(e);
+            }
             CommandError::operation(
                 ErrorCode::YubiKeyInitializationFailed,
                 format!("Failed to generate identity: {e}"),
@@ -0,0 +1,60 @@
+//! YubiKey Diagnostic Commands - PTY Session Recovery
+//!
+//! Thin wrappers for inspecting and recovering PTY sessions used to drive
+//! age, age-plugin-yubikey and ykman. Stalled sessions are also killed
+//! automatically by the watchdog; these commands cover manual recovery.
+//!
+//! Commands included:
+//! - get_pty_sessions: List active PTY sessions
+//! - kill_pty_session: Kill a wedged PTY session
+//! - get_plugin_protocol_info: age-plugin-yubikey version and negotiated protocol
+
+use crate::commands::command_types::{CommandError, ErrorCode};
+use crate::prelude::*;
+use crate::services::key_management::yubikey::YubiKeyManager;
+
+pub use crate::services::key_management::yubikey::domain::models::PluginProtocolInfo;
+pub use crate::services::key_management::yubikey::infrastructure::pty::PtySessionInfo;
+
+/// List active PTY sessions with their idle time
+#[tauri::command]
+#[specta::specta]
+#[instrument(skip_all)]
+pub async fn get_pty_sessions() -> Result<Vec<PtySessionInfo>, CommandError> {
+    let sessions = YubiKeyManager::pty_sessions();
+    debug!(session_count = sessions.len(), "Listed PTY sessions");
+    Ok(sessions)
+}
+
+/// Kill a PTY session; its operation fails and temporary files are removed
+#[tauri::command]
+#[specta::specta]
+#[instrument(skip_all)]
+pub async fn kill_pty_session(session_id: String) -> Result<(), CommandError> {
+    info!(session_id = %session_id, "Manual PTY session kill requested");
+
+    if YubiKeyManager::kill_pty_session(&session_id) {
+        Ok(())
+    } else {
+        Err(CommandError::operation(
+            ErrorCode::OperationNotFound,
+            format!("PTY session not found: {session_id}"),
+        )
+        .with_recovery_guidance("The session may have already finished"))
+    }
+}
+
+/// Report the bundled age-plugin-yubikey version and whether its plugin
+/// protocol matches the one this app speaks
+#[tauri::command]
+#[specta::specta]
+#[instrument(skip_all)]
+pub async fn get_plugin_protocol_info() -> Result<PluginProtocolInfo, CommandError> {
+    let info = YubiKeyManager::plugin_protocol_info().await?;
+    debug!(
+        plugin_version = ?info.plugin_version,
+        compatibility = ?info.compatibility,
+        "Checked age-plugin-yubikey protocol"
+    );
+    Ok(info)
+}
@@ -7,12 +7,14 @@
 //! - Device operations (list, init, register)
 //! - Vault integration (add to vault, list for vault)
 //! - Crypto operations (decrypt)
+//! - Diagnostics (PTY session listing and recovery)
 //!
 //! Internal implementation is hidden using `mod internal` pattern.
 
 // Public command modules - these expose #[tauri::command] functions
 pub mod crypto_commands;
 pub mod device_commands;
+pub mod diagnostic_commands;
 pub mod vault_commands;
 
 // Internal implementation was moved directly into command files for simplicity
@@ -20,4 +22,5 @@
 // Re-export key types for convenience
 pub use crypto_commands::*;
 pub use device_commands::*;
+pub use diagnostic_commands::*;
 pub use vault_commands::*;
@@ -6,23 +6,28 @@
 //!
 //! Commands included:
 //! - init_yubikey_for_vault: Initialize YubiKey and add to vault
-//! - register_yubikey_for_vault: Register existing YubiKey to vault
+//! - register_yubikey_for_vault: Register existing YubiKey to vault, optionally
+//!   fulfilling a reserved slot
+//! - reserve_yubikey_slot: Reserve a slot for a YubiKey that isn't at hand yet
 //! - list_available_yubikeys_for_vault: List YubiKeys available for vault
 //! - check_keymenubar_positions_available: Check vault display positions
 
-use crate::commands::command_types::{CommandError, CommandResponse, ErrorCode};
+use crate::commands::command_types::{CommandError, CommandResponse, ErrorCode, ValidateInput};
+use crate::commands::validation::{ExistingVaultId, NonEmpty, NonEmptyId, input_rules};
+use crate::commands::vault::refresh_onboarding;
 use crate::prelude::*;
 use crate::services::key_management::shared::KeyRegistry;
 use crate::services::key_management::shared::domain::models::key_lifecycle::KeyLifecycleStatus;
 use crate::services::key_management::shared::domain::models::{KeyType, VaultKey};
 use crate::services::key_management::yubikey::YubiKeyManager;
 use crate::services::key_management::yubikey::domain::models::{Pin, Serial};
-use crate::services::shared::infrastructure::sanitize_label;
+use crate::services::shared::infrastructure::{ChangeEvent, publish, publish_all, sanitize_label};
 use crate::services::vault;
 use chrono::Utc;
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 use tauri;
+use tracing::instrument;
 
 // Helper functions for vault operations
 /// Parameters for registering a YubiKey in vault
@@ -33,6 +38,8 @@ struct RegisterYubiKeyParams {
     device: crate::services::key_management::yubikey::domain::models::YubiKeyDevice,
     recovery_code_hash: String,
     lifecycle_status: KeyLifecycleStatus,
+    /// Reserved slot to convert in place instead of adding a new key
+    fulfills_reservation: Option<String>,
 }
 
 /// Helper to initialize YubiKeyManager with proper error handling
@@ -67,35 +74,65 @@ async fn load_vault(
 async fn register_yubikey_in_vault(
     mut vault: crate::services::vault::infrastructure::persistence::metadata::VaultMetadata,
     mut registry: KeyRegistry,
-    params: RegisterYubiKeyParams,
+    mut params: RegisterYubiKeyParams,
 ) -> Result<(VaultKey, String), Box<CommandError>> {
-    // Sanitize the label for use as key_id
-    let sanitized = sanitize_label(&params.label).map_err(|e| {
-        Box::new(
-            CommandError::validation(format!("Failed to sanitize label: {e}"))
-                .with_recovery_guidance("Provide a valid label without special characters"),
-        )
-    })?;
-
-    let key_registry_id = registry.add_yubikey_entry(
-        sanitized.sanitized.clone(), // key_id - sanitized
-        params.label.clone(),        // label - original display label
-        params.serial.clone(),
-        1u8,  // YubiKey retired slot number (not UI display slot)
-        82u8, // PIV slot 82 (first retired slot)
-        params.identity.to_recipient().to_string(),
-        params.identity.identity_tag().to_string(),
-        params.device.name.clone(), // Use actual device name as model
-        params.device.firmware_version.clone(),
-        params.recovery_code_hash.clone(),
-    );
-
-    registry.save().map_err(|e| {
-        Box::new(
-            CommandError::operation(ErrorCode::StorageFailed, e.to_string())
-                .with_recovery_guidance("Failed to save key registry"),
-        )
-    })?;
+    let (key_registry_id, created_at) = match params.fulfills_reservation.take() {
+        Some(reservation_id) => {
+            // The reservation's label and creation date carry over
+            let entry = registry.get_key(&reservation_id).ok_or_else(|| {
+                Box::new(CommandError::operation(
+                    ErrorCode::KeyNotFound,
+                    format!("Reserved YubiKey slot '{reservation_id}' not found"),
+                ))
+            })?;
+            params.label = entry.label().to_string();
+            let created_at = entry.created_at();
+
+            registry
+                .fulfill_yubikey_reservation(
+                    &reservation_id,
+                    params.serial.clone(),
+                    1u8,
+                    82u8,
+                    params.identity.to_recipient().to_string(),
+                    params.identity.identity_tag().to_string(),
+                    params.device.name.clone(),
+                    params.device.firmware_version.clone(),
+                    params.recovery_code_hash.clone(),
+                )
+                .map_err(|e| Box::new(CommandError::operation(ErrorCode::InvalidKeyState, e)))?;
+            save_registry(&registry)?;
+            publish(ChangeEvent::KeyReservationFulfilled {
+                key_id: reservation_id.clone(),
+                vault_id: vault.vault_id().to_string(),
+            });
+            (reservation_id, created_at)
+        }
+        None => {
+            // Sanitize the label for use as key_id
+            let sanitized = sanitize_key_label(&params.label)?;
+
+            let key_registry_id = registry.add_yubikey_entry(
+                sanitized,            // key_id - sanitized
+                params.label.clone(), // label - original display label
+                params.serial.clone(),
+                1u8,  // YubiKey retired slot number (not UI display slot)
+                82u8, // PIV slot 82 (first retired slot)
+                params.identity.to_recipient().to_string(),
+                params.identity.identity_tag().to_string(),
+                params.device.name.clone(), // Use actual device name as model
+                params.device.firmware_version.clone(),
+                params.recovery_code_hash.clone(),
+            );
+
+            save_registry(&registry)?;
+            publish(ChangeEvent::KeyAdded {
+                key_id: key_registry_id.clone(),
+                label: params.label.clone(),
+            });
+            (key_registry_id, Utc::now())
+        }
+    };
 
     // Add YubiKey recipient to vault metadata
     use crate::services::vault::infrastructure::persistence::metadata::{
@@ -114,7 +151,7 @@ async fn register_yubikey_in_vault(
         },
         public_key: params.identity.to_recipient().to_string(),
         label: params.label.clone(),
-        created_at: Utc::now(),
+        created_at,
     };
 
     vault.add_recipient(recipient);
@@ -134,13 +171,57 @@ async fn register_yubikey_in_vault(
             serial: params.serial,
             firmware_version: params.device.firmware_version.clone(),
         },
-        created_at: Utc::now(),
+        created_at,
         last_used: None,
     };
 
     Ok((key_reference, params.recovery_code_hash))
 }
 
+/// Helper to sanitize a label for use as a key ID
+fn sanitize_key_label(label: &str) -> Result<String, Box<CommandError>> {
+    sanitize_label(label)
+        .map(|sanitized| sanitized.sanitized)
+        .map_err(|e| {
+            Box::new(
+                CommandError::validation(format!("Failed to sanitize label: {e}"))
+                    .with_recovery_guidance("Provide a valid label without special characters"),
+            )
+        })
+}
+
+/// Helper to save the key registry
+fn save_registry(registry: &KeyRegistry) -> Result<(), Box<CommandError>> {
+    registry.save().map_err(|e| {
+        Box::new(
+            CommandError::operation(ErrorCode::StorageFailed, e.to_string())
+                .with_recovery_guidance("Failed to save key registry"),
+        )
+    })
+}
+
+/// Helper to check that a key is a reserved YubiKey slot of the vault
+fn check_reservation(
+    registry: &KeyRegistry,
+    reservation_id: &str,
+    vault_id: &str,
+) -> Result<(), Box<CommandError>> {
+    if registry
+        .pending_yubikeys(vault_id)
+        .iter()
+        .any(|(key_id, _)| key_id.as_str() == reservation_id)
+    {
+        return Ok(());
+    }
+    Err(Box::new(
+        CommandError::operation(
+            ErrorCode::InvalidKeyState,
+            format!("'{reservation_id}' is not a reserved YubiKey slot of this vault"),
+        )
+        .with_recovery_guidance("Choose one of the vault's pending YubiKeys"),
+    ))
+}
+
 /// Helper to check for duplicate YubiKey in vault
 fn check_duplicate_yubikey_in_vault(
     vault: &crate::services::vault::infrastructure::persistence::metadata::VaultMetadata,
@@ -177,6 +258,18 @@ pub struct YubiKeyInitForVaultParams {
     pub pin: String,
     pub label: String,
     pub vault_id: String,
+    /// Send the PIN even if only one attempt is left
+    #[serde(default)]
+    pub accept_last_attempt: Option<bool>,
+}
+
+input_rules! {
+    YubiKeyInitForVaultParams {
+        vault_id("Vault ID"): [ExistingVaultId],
+        serial("YubiKey serial"): [NonEmptyId],
+        pin("PIN"): [NonEmpty],
+        label("Key label"): [NonEmpty],
+    }
 }
 
 /// YubiKey registration parameters for vault
@@ -186,6 +279,32 @@ pub struct RegisterYubiKeyForVaultParams {
     pub pin: String,
     pub label: String,
     pub vault_id: String,
+    /// Key ID of a reserved slot to fulfill; the slot's label is kept
+    #[serde(default)]
+    pub fulfills_reservation: Option<String>,
+}
+
+input_rules! {
+    RegisterYubiKeyForVaultParams {
+        vault_id("Vault ID"): [ExistingVaultId],
+        serial("YubiKey serial"): [NonEmptyId],
+        pin("PIN"): [NonEmpty],
+        label("Key label"): [NonEmpty],
+    }
+}
+
+/// Parameters for reserving a YubiKey slot in a vault
+#[derive(Debug, Serialize, Deserialize, specta::Type)]
+pub struct ReserveYubiKeySlotParams {
+    pub vault_id: String,
+    pub label: String,
+}
+
+input_rules! {
+    ReserveYubiKeySlotParams {
+        vault_id("Vault ID"): [ExistingVaultId],
+        label("Key label"): [NonEmpty],
+    }
 }
 
 /// Result from YubiKey operations
@@ -204,6 +323,7 @@ pub struct YubiKeyVaultResult {
 /// Delegates YubiKey operations to YubiKeyManager, handles vault integration
 #[tauri::command]
 #[specta::specta]
+#[instrument(skip_all)]
 pub async fn init_yubikey_for_vault(
     input: YubiKeyInitForVaultParams,
 ) -> CommandResponse<YubiKeyVaultResult> {
@@ -212,6 +332,7 @@ pub async fn init_yubikey_for_vault(
         &input.serial[..8.min(input.serial.len())],
         input.vault_id
     );
+    input.validate()?;
 
     // Validate vault and check for duplicates
     let vault = load_vault(&input.vault_id).await?;
@@ -247,16 +368,24 @@ pub async fn init_yubikey_for_vault(
     );
 
     let (device, identity, recovery_code_hash) = manager
-        .initialize_device(
+        .guard_pin_attempt(
             &serial,
             &pin,
-            slot,
-            recovery_placeholder.clone(),
-            Some(input.label.clone()),
+            input.accept_last_attempt.unwrap_or(false),
+            manager.initialize_device(
+                &serial,
+                &pin,
+                slot,
+                recovery_placeholder.clone(),
+                Some(input.label.clone()),
+            ),
         )
         .await
         .map_err(|e| {
             error!("initialize_device failed with error: {}", e);
+            if e.is_pin_guard() {
+                return Box::new(CommandError::from(e));
+            }
             Box::new(
                 CommandError::operation(
                     ErrorCode::YubiKeyInitializationFailed,
@@ -279,11 +408,13 @@ pub async fn init_yubikey_for_vault(
             device,
             recovery_code_hash,
             lifecycle_status: KeyLifecycleStatus::Active,
+            fulfills_reservation: None,
         },
     )
     .await?;
 
     info!("Successfully initialized YubiKey and added to vault");
+    refresh_onboarding().await;
 
     Ok(YubiKeyVaultResult {
         success: true,
@@ -296,6 +427,7 @@ pub async fn init_yubikey_for_vault(
 /// Delegates YubiKey operations to YubiKeyManager, handles vault integration
 #[tauri::command]
 #[specta::specta]
+#[instrument(skip_all)]
 pub async fn register_yubikey_for_vault(
     input: RegisterYubiKeyForVaultParams,
 ) -> CommandResponse<YubiKeyVaultResult> {
@@ -304,12 +436,16 @@ pub async fn register_yubikey_for_vault(
         &input.serial[..8.min(input.serial.len())],
         input.vault_id
     );
+    input.validate()?;
 
     // Validate vault exists
     let vault = load_vault(&input.vault_id).await?;
     let registry = crate::services::key_management::shared::KeyManager::new()
         .load_registry()
         .unwrap_or_else(|_| KeyRegistry::new());
+    if let Some(reservation_id) = &input.fulfills_reservation {
+        check_reservation(&registry, reservation_id, &input.vault_id)?;
+    }
 
     // Initialize YubiKey manager and validate device
     let manager = create_yubikey_manager().await?;
@@ -322,7 +458,7 @@ pub async fn register_yubikey_for_vault(
 
     // Validate device exists and has identity
     let device = manager
-        .detect_device(&serial)
+        .detect_device(&serial, None)
         .await
         .map_err(|e| {
             Box::new(
@@ -398,11 +534,13 @@ pub async fn register_yubikey_for_vault(
             device,
             recovery_code_hash: recovery_placeholder,
             lifecycle_status: KeyLifecycleStatus::Active, // Registered was confusing - it means active
+            fulfills_reservation: input.fulfills_reservation,
         },
     )
     .await?;
 
     info!("Successfully registered YubiKey for vault");
+    refresh_onboarding().await;
 
     Ok(YubiKeyVaultResult {
         success: true,
@@ -410,3 +548,59 @@ pub async fn register_yubikey_for_vault(
         recovery_code_hash,
     })
 }
+
+/// Reserve a YubiKey slot in a vault for hardware that isn't at hand yet
+///
+/// The placeholder is listed with the vault's keys as a pending YubiKey. It
+/// has no recipient, so encryption is unaffected until
+/// `register_yubikey_for_vault` fulfills it.
+#[tauri::command]
+#[specta::specta]
+#[instrument(skip_all, fields(vault_id = %input.vault_id))]
+pub async fn reserve_yubikey_slot(input: ReserveYubiKeySlotParams) -> CommandResponse<VaultKey> {
+    input.validate()?;
+
+    load_vault(&input.vault_id).await?;
+    let key_id = sanitize_key_label(&input.label)?;
+    let mut registry = crate::services::key_management::shared::KeyManager::new()
+        .load_registry()
+        .map_err(|e| {
+            Box::new(
+                CommandError::operation(ErrorCode::StorageFailed, e.to_string())
+                    .with_recovery_guidance("Failed to load key registry"),
+            )
+        })?;
+
+    registry
+        .reserve_yubikey_slot(key_id.clone(), input.label.clone(), input.vault_id.clone())
+        .map_err(|e| {
+            Box::new(
+                CommandError::operation(ErrorCode::KeyAlreadyExists, e)
+                    .with_recovery_guidance("Choose a different label"),
+            )
+        })?;
+    save_registry(&registry)?;
+    publish_all(vec![
+        ChangeEvent::KeyAdded {
+            key_id: key_id.clone(),
+            label: input.label.clone(),
+        },
+        ChangeEvent::KeyAttached {
+            key_id: key_id.clone(),
+            vault_id: input.vault_id.clone(),
+        },
+    ]);
+
+    let entry = registry.get_key(&key_id).ok_or_else(|| {
+        Box::new(CommandError::operation(
+            ErrorCode::StorageFailed,
+            "Reserved YubiKey slot missing after saving",
+        ))
+    })?;
+    info!(key_id = %key_id, "Reserved YubiKey slot");
+    Ok(VaultKey::from_registry_entry(
+        key_id.clone(),
+        entry,
+        entry.lifecycle_status(),
+    ))
+}
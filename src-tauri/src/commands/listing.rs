@@ -0,0 +1,168 @@
+//! Sorting and pagination for list commands
+//!
+//! List commands take an optional `sort` and `page` and return one page of a
+//! stable sort plus the total count. Equal sort values are ordered by id, so
+//! walking the pages never repeats or skips an entry.
+
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+
+/// Page size when a list command gets no `page`
+pub const DEFAULT_PAGE_LIMIT: usize = 200;
+
+/// Largest page a list command returns; bigger limits are capped
+pub const MAX_PAGE_LIMIT: usize = 1000;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+#[serde(rename_all = "snake_case")]
+pub enum SortDirection {
+    #[default]
+    Asc,
+    Desc,
+}
+
+/// Field and direction to sort a list by
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+pub struct SortSpec<F> {
+    pub field: F,
+    #[serde(default)]
+    pub direction: SortDirection,
+}
+
+/// Which slice of a sorted list to return
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+pub struct PageRequest {
+    #[serde(default)]
+    pub offset: usize,
+    /// Defaults to `DEFAULT_PAGE_LIMIT`, capped at `MAX_PAGE_LIMIT`
+    pub limit: Option<usize>,
+}
+
+impl PageRequest {
+    /// Page size actually used: at least 1, at most `MAX_PAGE_LIMIT`
+    pub fn effective_limit(&self) -> usize {
+        self.limit
+            .unwrap_or(DEFAULT_PAGE_LIMIT)
+            .clamp(1, MAX_PAGE_LIMIT)
+    }
+}
+
+/// An entry of a sortable list
+pub trait Listable {
+    type SortField: Copy;
+
+    /// Unique id, the tiebreaker for equal sort values
+    fn list_id(&self) -> &str;
+
+    fn compare_by(&self, other: &Self, field: Self::SortField) -> Ordering;
+}
+
+/// One page of a sorted list
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    /// Entries in the whole list, not just this page
+    pub total_count: usize,
+}
+
+/// Sort `items` and cut out the requested page
+///
+/// `default_field` is used when `sort` is omitted.
+pub fn sort_and_paginate<T: Listable>(
+    mut items: Vec<T>,
+    sort: Option<SortSpec<T::SortField>>,
+    default_field: T::SortField,
+    page: Option<PageRequest>,
+) -> Page<T> {
+    let sort = sort.unwrap_or(SortSpec {
+        field: default_field,
+        direction: SortDirection::Asc,
+    });
+    items.sort_by(|a, b| {
+        let ordering = a.compare_by(b, sort.field);
+        let ordering = match sort.direction {
+            SortDirection::Asc => ordering,
+            SortDirection::Desc => ordering.reverse(),
+        };
+        ordering.then_with(|| a.list_id().cmp(b.list_id()))
+    });
+
+    let page = page.unwrap_or_default();
+    let total_count = items.len();
+    let items = items
+        .into_iter()
+        .skip(page.offset)
+        .take(page.effective_limit())
+        .collect();
+
+    Page { items, total_count }
+}
+
+/// Compare text the way the UI shows it: case-insensitive
+pub fn compare_text(a: &str, b: &str) -> Ordering {
+    a.to_lowercase().cmp(&b.to_lowercase())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct Row {
+        id: String,
+        group: u8,
+    }
+
+    impl Listable for Row {
+        type SortField = ();
+
+        fn list_id(&self) -> &str {
+            &self.id
+        }
+
+        fn compare_by(&self, other: &Self, _field: ()) -> Ordering {
+            self.group.cmp(&other.group)
+        }
+    }
+
+    fn rows() -> Vec<Row> {
+        ["c", "a", "d", "b"]
+            .iter()
+            .enumerate()
+            .map(|(i, id)| Row {
+                id: id.to_string(),
+                group: (i % 2) as u8,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_ties_are_broken_by_id() {
+        let page = sort_and_paginate(rows(), None, (), None);
+        let ids: Vec<_> = page.items.iter().map(|row| row.id.as_str()).collect();
+        assert_eq!(ids, vec!["a", "c", "b", "d"]);
+        assert_eq!(page.total_count, 4);
+
+        let desc = SortSpec {
+            field: (),
+            direction: SortDirection::Desc,
+        };
+        let page = sort_and_paginate(rows(), Some(desc), (), None);
+        let ids: Vec<_> = page.items.iter().map(|row| row.id.as_str()).collect();
+        assert_eq!(ids, vec!["b", "d", "a", "c"]);
+    }
+
+    #[test]
+    fn test_page_limits() {
+        let page = |offset, limit| PageRequest { offset, limit };
+        assert_eq!(page(0, None).effective_limit(), DEFAULT_PAGE_LIMIT);
+        assert_eq!(page(0, Some(0)).effective_limit(), 1);
+        assert_eq!(page(0, Some(1_000_000)).effective_limit(), MAX_PAGE_LIMIT);
+
+        let result = sort_and_paginate(rows(), None, (), Some(page(3, Some(10))));
+        assert_eq!(result.items.len(), 1);
+        assert_eq!(result.total_count, 4);
+        let result = sort_and_paginate(rows(), None, (), Some(page(10, Some(10))));
+        assert!(result.items.is_empty());
+    }
+}
@@ -4,8 +4,13 @@
 //! and the core Rust modules. All commands include proper validation,
 //! error handling, and security checks.
 
+pub mod clipboard;
 pub mod crypto;
+pub mod diagnostics;
 pub mod file;
+pub mod listing;
+pub mod storage;
+pub mod validation;
 pub mod vault;
 
 // Key management commands - organized by domain
@@ -17,8 +22,12 @@
 
 // Re-export all types for Tauri handler
 pub use crate::types::*;
+pub use clipboard::*;
 pub use crypto::*;
+pub use diagnostics::*;
 pub use file::*;
+pub use listing::{PageRequest, SortDirection, SortSpec};
+pub use storage::*;
 pub use vault::*;
 
 // Re-export key management commands
@@ -0,0 +1,130 @@
+//! Storage location commands
+//!
+//! Reports where Barqly Vault keeps its data: the active mode (standard or
+//! portable) and every resolved directory, and what deleting files at a
+//! location actually guarantees. Also reports how much space the app's
+//! working data takes and sets the quotas that keep it in check, and
+//! whether the directories holding keys or plaintext are kept out of the
+//! search index.
+
+use crate::commands::types::{CommandError, CommandResponse, ErrorCode};
+use crate::prelude::*;
+use crate::services::crypto::CryptoManager;
+use crate::services::shared::infrastructure::io::{self, SecureDeleteCapability};
+use crate::services::shared::infrastructure::path_management::{self, StoragePaths};
+use crate::services::vault::VaultManager;
+use crate::services::vault::domain::models::{
+    SensitiveDirectoryStatus, StorageQuotas, StorageUsageReport,
+};
+use crate::services::vault::infrastructure::persistence::AppConfig;
+use std::path::{Path, PathBuf};
+
+/// Resolved storage locations and which mode is active
+///
+/// `profile_warning` is set when portable mode is active but an existing
+/// profile in the default location is being ignored.
+#[tauri::command]
+#[specta::specta]
+#[instrument]
+pub async fn get_storage_paths() -> CommandResponse<StoragePaths> {
+    path_management::get_storage_paths().map_err(|e| {
+        Box::new(
+            CommandError::operation(ErrorCode::StorageFailed, "Failed to resolve storage paths")
+                .with_details(e.to_string()),
+        )
+    })
+}
+
+/// How files at `path` are deleted and how far that can be trusted
+///
+/// Overwriting doesn't destroy data on SSDs, copy-on-write filesystems,
+/// network shares, or synced folders; the honesty level says so instead of
+/// implying a guarantee.
+#[tauri::command]
+#[specta::specta]
+#[instrument]
+pub async fn get_secure_delete_capability(path: String) -> CommandResponse<SecureDeleteCapability> {
+    if path.trim().is_empty() {
+        return Err(Box::new(CommandError::validation("Path is required")));
+    }
+    Ok(io::get_secure_delete_capability(Path::new(&path)))
+}
+
+/// Space used by each category of app data, with what could be purged
+///
+/// Covers staging, quarantine, metadata snapshots, cache, logs and trash,
+/// each against its quota.
+#[tauri::command]
+#[specta::specta]
+#[instrument]
+pub async fn get_storage_usage() -> CommandResponse<StorageUsageReport> {
+    VaultManager::new().get_storage_usage().map_err(|e| {
+        Box::new(
+            CommandError::operation(ErrorCode::StorageFailed, "Failed to measure storage usage")
+                .with_details(e.to_string()),
+        )
+    })
+}
+
+/// Whether each directory holding keys or plaintext is kept out of the
+/// search index
+///
+/// Covers the keys directory, staging, each vault's quarantine and the
+/// output of every pending cleanup session. A directory that doesn't exist
+/// yet has no protection to report.
+#[tauri::command]
+#[specta::specta]
+#[instrument]
+pub async fn get_indexing_protection_status() -> CommandResponse<Vec<SensitiveDirectoryStatus>> {
+    let session_dirs: Vec<PathBuf> = CryptoManager::new()
+        .list_cleanup_sessions()
+        .inspect_err(|e| warn!(error = %e, "Failed to list cleanup sessions"))
+        .unwrap_or_default()
+        .into_iter()
+        .map(|session| PathBuf::from(session.output_dir))
+        .collect();
+
+    VaultManager::new()
+        .get_indexing_protection_status(&session_dirs)
+        .map_err(|e| {
+            Box::new(
+                CommandError::operation(
+                    ErrorCode::StorageFailed,
+                    "Failed to check indexing protection",
+                )
+                .with_details(e.to_string()),
+            )
+        })
+}
+
+/// Set the size limits of each category of app data
+///
+/// Takes effect at the next storage cleanup: on startup and before each
+/// encryption or decryption.
+#[tauri::command]
+#[specta::specta]
+#[instrument]
+pub async fn set_storage_quotas(quotas: StorageQuotas) -> CommandResponse<()> {
+    let save = AppConfig::load().and_then(|mut config| {
+        config.storage_quotas = quotas;
+        config.save()
+    });
+    save.map_err(|e| {
+        Box::new(
+            CommandError::operation(
+                ErrorCode::StorageFailed,
+                format!("Failed to save storage quotas: {}", e),
+            )
+            .with_recovery_guidance("Check that the config directory is writable"),
+        )
+    })
+}
+
+/// Purge app data over its quotas before an operation that needs disk space
+///
+/// Best effort: a failure is logged and the operation goes ahead.
+pub(crate) async fn reclaim_storage() {
+    if let Err(e) = VaultManager::new().enforce_storage_quotas() {
+        warn!(error = %e, "Failed to enforce storage quotas");
+    }
+}
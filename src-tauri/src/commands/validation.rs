@@ -0,0 +1,467 @@
+//! Declarative validation of command inputs
+//!
+//! Each command input declares its per-field rules with `input_rules!`,
+//! which implements `ValidateInput` for it. Commands call `validate()` before
+//! doing anything else. A failure is a `CommandError` with code
+//! `ValidationFailed` and a `ValidationFailure` naming the field, the rule it
+//! broke and a localization key, so the frontend can mark the offending form
+//! field.
+//!
+//! Validators are small unit structs implementing `Validator` for the value
+//! type they check; `Option` fields are checked only when present. The
+//! checks themselves live in `ValidationHelper`, so messages match the rest
+//! of the app.
+
+use crate::commands::types::{
+    ByteSize, CommandError, ValidationFailure, ValidationHelper, ValidationRule,
+};
+use crate::services::key_management::passphrase::normalize_passphrase;
+use crate::services::vault::vault_exists_sync;
+use tracing::debug;
+
+/// A rule a field value must satisfy
+pub trait Validator<T: ?Sized> {
+    fn rule(&self) -> ValidationRule;
+
+    /// Check `value`; `label` names the field in messages
+    fn check(&self, label: &str, value: &T) -> Result<(), Box<CommandError>>;
+}
+
+/// A command input field that validators can check
+pub trait FieldValue {
+    type Value: ?Sized;
+
+    /// The value to check, or `None` for an absent optional field
+    fn field_value(&self) -> Option<&Self::Value>;
+}
+
+impl FieldValue for String {
+    type Value = str;
+
+    fn field_value(&self) -> Option<&str> {
+        Some(self)
+    }
+}
+
+impl<T> FieldValue for Vec<T> {
+    type Value = [T];
+
+    fn field_value(&self) -> Option<&[T]> {
+        Some(self)
+    }
+}
+
+impl FieldValue for ByteSize {
+    type Value = ByteSize;
+
+    fn field_value(&self) -> Option<&ByteSize> {
+        Some(self)
+    }
+}
+
+impl FieldValue for u32 {
+    type Value = u32;
+
+    fn field_value(&self) -> Option<&u32> {
+        Some(self)
+    }
+}
+
+impl<T: FieldValue> FieldValue for Option<T> {
+    type Value = T::Value;
+
+    fn field_value(&self) -> Option<&T::Value> {
+        self.as_ref().and_then(FieldValue::field_value)
+    }
+}
+
+/// Check one field, turning a failure into a structured validation error
+pub fn check_field<F, V>(
+    field: &str,
+    label: &str,
+    value: &F,
+    validator: &V,
+) -> Result<(), Box<CommandError>>
+where
+    F: FieldValue + ?Sized,
+    V: Validator<F::Value>,
+{
+    let Some(value) = value.field_value() else {
+        return Ok(());
+    };
+    validator.check(label, value).map_err(|error| {
+        let guidance = error.recovery_guidance.clone();
+        let mut failure = invalid(field, validator.rule(), error.message);
+        failure.recovery_guidance = guidance;
+        Box::new(failure)
+    })
+}
+
+/// Validation error for `field`, for checks a command makes itself
+pub fn invalid(field: &str, rule: ValidationRule, message: impl Into<String>) -> CommandError {
+    debug!(field, rule = rule.as_str(), "Input validation failed");
+    CommandError::validation_failed(ValidationFailure::new(field, rule), message)
+}
+
+/// Implement `ValidateInput` for a command input from per-field rules
+///
+/// ```ignore
+/// input_rules! {
+///     AttachKeyRequest {
+///         key_id("Key ID"): [NonEmptyId],
+///         vault_id("Vault ID"): [ExistingVaultId],
+///     }
+/// }
+/// ```
+///
+/// Fields are checked in declaration order and each field's rules in order;
+/// the first failure is returned. The quoted label names the field in
+/// messages. A trailing `then <fn>` runs a command-specific check after the
+/// field rules.
+macro_rules! input_rules {
+    (
+        $input:ident {
+            $($field:ident ($label:literal): [$($rule:expr),+ $(,)?]),* $(,)?
+        }
+        $(then $extra:path)?
+    ) => {
+        impl $crate::commands::types::ValidateInput for $input {
+            fn validate(&self) -> Result<(), Box<$crate::commands::types::CommandError>> {
+                $($(
+                    $crate::commands::validation::check_field(
+                        stringify!($field),
+                        $label,
+                        &self.$field,
+                        &$rule,
+                    )?;
+                )+)*
+                $($extra(self)?;)?
+                Ok(())
+            }
+        }
+    };
+}
+pub(crate) use input_rules;
+
+/// Free text or a secret that must not be blank
+pub struct NonEmpty;
+
+impl Validator<str> for NonEmpty {
+    fn rule(&self) -> ValidationRule {
+        ValidationRule::NonEmpty
+    }
+
+    fn check(&self, label: &str, value: &str) -> Result<(), Box<CommandError>> {
+        ValidationHelper::validate_not_empty(value, label)
+    }
+}
+
+/// Identifier of a key, archive, session or other record
+pub struct NonEmptyId;
+
+impl Validator<str> for NonEmptyId {
+    fn rule(&self) -> ValidationRule {
+        ValidationRule::NonEmptyId
+    }
+
+    fn check(&self, label: &str, value: &str) -> Result<(), Box<CommandError>> {
+        ValidationHelper::validate_not_empty(value, label)
+    }
+}
+
+/// List that needs at least one entry, none of them blank
+///
+/// The label names one entry, e.g. "archive".
+pub struct NonEmptyList;
+
+impl<T: AsRef<str>> Validator<[T]> for NonEmptyList {
+    fn rule(&self) -> ValidationRule {
+        ValidationRule::NonEmptyList
+    }
+
+    fn check(&self, label: &str, value: &[T]) -> Result<(), Box<CommandError>> {
+        if value.is_empty() {
+            return Err(Box::new(
+                CommandError::validation(format!("At least one {label} must be selected"))
+                    .with_recovery_guidance(format!("Please select one or more {label}s")),
+            ));
+        }
+        if value.iter().any(|entry| entry.as_ref().trim().is_empty()) {
+            return Err(Box::new(
+                CommandError::validation(format!("A blank {label} was given"))
+                    .with_recovery_guidance("Remove the empty entry and try again"),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// ID of a vault on this device
+pub struct ExistingVaultId;
+
+impl Validator<str> for ExistingVaultId {
+    fn rule(&self) -> ValidationRule {
+        ValidationRule::ExistingVaultId
+    }
+
+    fn check(&self, label: &str, value: &str) -> Result<(), Box<CommandError>> {
+        ValidationHelper::validate_not_empty(value, label)?;
+        if !vault_exists_sync(value) {
+            return Err(Box::new(
+                CommandError::validation(format!("Vault '{value}' not found"))
+                    .with_recovery_guidance("Select a vault from your vault list"),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Path to an existing regular file
+pub struct ExistingFile;
+
+impl Validator<str> for ExistingFile {
+    fn rule(&self) -> ValidationRule {
+        ValidationRule::ExistingFile
+    }
+
+    fn check(&self, label: &str, value: &str) -> Result<(), Box<CommandError>> {
+        ValidationHelper::validate_not_empty(value, &format!("{label} path"))?;
+        ValidationHelper::validate_path_exists(value, label)?;
+        ValidationHelper::validate_is_file(value, label)
+    }
+}
+
+/// Path to an existing directory
+pub struct ExistingDirectory;
+
+impl Validator<str> for ExistingDirectory {
+    fn rule(&self) -> ValidationRule {
+        ValidationRule::ExistingDirectory
+    }
+
+    fn check(&self, label: &str, value: &str) -> Result<(), Box<CommandError>> {
+        ValidationHelper::validate_not_empty(value, label)?;
+        ValidationHelper::validate_path_exists(value, label)?;
+        ValidationHelper::validate_is_directory(value, label)
+    }
+}
+
+/// Path inside the user's home directory and outside system folders
+pub struct PathWithinAllowedRoots;
+
+impl Validator<str> for PathWithinAllowedRoots {
+    fn rule(&self) -> ValidationRule {
+        ValidationRule::PathWithinAllowedRoots
+    }
+
+    fn check(&self, label: &str, value: &str) -> Result<(), Box<CommandError>> {
+        ValidationHelper::validate_not_empty(value, label)?;
+        ValidationHelper::validate_safe_user_path(value)
+    }
+}
+
+/// Key label: up to 24 characters, no filesystem-unsafe characters
+pub struct LabelFormat;
+
+impl Validator<str> for LabelFormat {
+    fn rule(&self) -> ValidationRule {
+        ValidationRule::LabelFormat
+    }
+
+    fn check(&self, _label: &str, value: &str) -> Result<(), Box<CommandError>> {
+        ValidationHelper::validate_key_label(value)
+    }
+}
+
+/// New passphrase meeting the minimum strength once normalized
+pub struct PassphraseStrength;
+
+impl Validator<str> for PassphraseStrength {
+    fn rule(&self) -> ValidationRule {
+        ValidationRule::PassphraseStrength
+    }
+
+    fn check(&self, _label: &str, value: &str) -> Result<(), Box<CommandError>> {
+        ValidationHelper::validate_passphrase_strength(&normalize_passphrase(value))
+    }
+}
+
+/// Byte count that must be above zero
+pub struct PositiveByteSize;
+
+impl Validator<ByteSize> for PositiveByteSize {
+    fn rule(&self) -> ValidationRule {
+        ValidationRule::PositiveByteSize
+    }
+
+    fn check(&self, label: &str, value: &ByteSize) -> Result<(), Box<CommandError>> {
+        if *value == ByteSize::ZERO {
+            return Err(Box::new(CommandError::validation(format!(
+                "{label} must be greater than zero"
+            ))));
+        }
+        Ok(())
+    }
+}
+
+/// Number within inclusive bounds
+pub struct InRange {
+    pub min: u32,
+    pub max: u32,
+}
+
+impl Validator<u32> for InRange {
+    fn rule(&self) -> ValidationRule {
+        ValidationRule::InRange
+    }
+
+    fn check(&self, label: &str, value: &u32) -> Result<(), Box<CommandError>> {
+        if !(self.min..=self.max).contains(value) {
+            return Err(Box::new(CommandError::validation(format!(
+                "{label} must be between {} and {}",
+                self.min, self.max
+            ))));
+        }
+        Ok(())
+    }
+}
+
+/// Text of at most `max` bytes
+pub struct MaxLength {
+    pub max: usize,
+}
+
+impl Validator<str> for MaxLength {
+    fn rule(&self) -> ValidationRule {
+        ValidationRule::MaxLength
+    }
+
+    fn check(&self, label: &str, value: &str) -> Result<(), Box<CommandError>> {
+        ValidationHelper::validate_length(value, label, 0, self.max)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::types::{ErrorCode, ValidateInput};
+
+    struct ExampleInput {
+        name: String,
+        tags: Vec<String>,
+        limit: Option<u32>,
+        size: ByteSize,
+    }
+
+    fn require_short_name(input: &ExampleInput) -> Result<(), Box<CommandError>> {
+        if input.name.len() > 8 {
+            return Err(Box::new(invalid(
+                "name",
+                ValidationRule::Format,
+                "too long",
+            )));
+        }
+        Ok(())
+    }
+
+    input_rules! {
+        ExampleInput {
+            name("Name"): [NonEmpty],
+            tags("tag"): [NonEmptyList],
+            limit("Limit"): [InRange { min: 1, max: 10 }],
+            size("Size"): [PositiveByteSize],
+        }
+        then require_short_name
+    }
+
+    fn example() -> ExampleInput {
+        ExampleInput {
+            name: "backup".to_string(),
+            tags: vec!["a".to_string()],
+            limit: None,
+            size: ByteSize(1),
+        }
+    }
+
+    fn failure(input: ExampleInput) -> (ValidationFailure, String) {
+        let error = input.validate().unwrap_err();
+        assert!(matches!(error.code, ErrorCode::ValidationFailed));
+        (error.validation.unwrap(), error.message)
+    }
+
+    #[test]
+    fn test_rules_report_field_and_rule() {
+        assert!(example().validate().is_ok());
+
+        let cases: Vec<(ExampleInput, &str, ValidationRule)> = vec![
+            (
+                ExampleInput {
+                    name: "  ".to_string(),
+                    ..example()
+                },
+                "name",
+                ValidationRule::NonEmpty,
+            ),
+            (
+                ExampleInput {
+                    tags: vec![],
+                    ..example()
+                },
+                "tags",
+                ValidationRule::NonEmptyList,
+            ),
+            (
+                ExampleInput {
+                    limit: Some(11),
+                    ..example()
+                },
+                "limit",
+                ValidationRule::InRange,
+            ),
+            (
+                ExampleInput {
+                    size: ByteSize::ZERO,
+                    ..example()
+                },
+                "size",
+                ValidationRule::PositiveByteSize,
+            ),
+            (
+                ExampleInput {
+                    name: "much too long".to_string(),
+                    ..example()
+                },
+                "name",
+                ValidationRule::Format,
+            ),
+        ];
+
+        for (input, field, rule) in cases {
+            let (failure, _) = failure(input);
+            assert_eq!(failure.field, field);
+            assert_eq!(failure.rule, rule);
+            assert_eq!(failure.message_key, format!("validation.{}", rule.as_str()));
+        }
+    }
+
+    #[test]
+    fn test_messages_use_field_label() {
+        let (_, message) = failure(ExampleInput {
+            name: String::new(),
+            ..example()
+        });
+        assert_eq!(message, "Name cannot be empty");
+
+        let (_, message) = failure(ExampleInput {
+            limit: Some(0),
+            ..example()
+        });
+        assert_eq!(message, "Limit must be between 1 and 10");
+
+        let (_, message) = failure(ExampleInput {
+            tags: vec![],
+            ..example()
+        });
+        assert_eq!(message, "At least one tag must be selected");
+    }
+}
@@ -0,0 +1,335 @@
+//! Vault archive index commands
+//!
+//! Search past encryptions by comment, archive name, or date, edit an
+//! archive's comment without re-encrypting it, mark archives immutable,
+//! repair archives from their parity, replace a few small files with a
+//! patch instead of a new archive, and choose what happens when an archive
+//! name is already another vault's in a shared output folder.
+
+use crate::commands::command_types::{CommandError, CommandResponse, ErrorCode, ValidateInput};
+use crate::commands::listing::{Listable, PageRequest, SortSpec, compare_text, sort_and_paginate};
+use crate::commands::validation::{ExistingVaultId, NonEmptyId, input_rules};
+use crate::services::shared::infrastructure::progress::{OperationKind, begin_exclusive_operation};
+use crate::services::vault::VaultManager;
+use crate::services::vault::domain::VaultError;
+use crate::services::vault::domain::models::{
+    ArchiveIndexEntry, ArchiveListing, ArchivePatchEntry, ArchiveRepairReport, ArchiveSearchMatch,
+    CrossVaultNamePolicy, PatchReplacement,
+};
+use crate::services::vault::infrastructure::persistence::AppConfig;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use tracing::instrument;
+
+/// Input for searching a vault's archives
+#[derive(Debug, Deserialize, specta::Type)]
+pub struct SearchArchivesRequest {
+    pub vault_id: String,
+    /// Case-insensitive substring of a comment, archive name, or date
+    pub query: String,
+}
+
+input_rules! {
+    SearchArchivesRequest {
+        vault_id("Vault ID"): [ExistingVaultId],
+    }
+}
+
+/// Ranked search results
+#[derive(Debug, Serialize, specta::Type)]
+pub struct SearchArchivesResponse {
+    /// Best match first
+    pub matches: Vec<ArchiveSearchMatch>,
+}
+
+/// Input for editing an archive's comment
+#[derive(Debug, Deserialize, specta::Type)]
+pub struct UpdateArchiveCommentRequest {
+    pub vault_id: String,
+    pub archive_id: String,
+    /// New comment; empty or missing clears it
+    pub comment: Option<String>,
+}
+
+input_rules! {
+    UpdateArchiveCommentRequest {
+        vault_id("Vault ID"): [ExistingVaultId],
+        archive_id("Archive ID"): [NonEmptyId],
+    }
+}
+
+/// Fields `list_archives` can sort by
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+#[serde(rename_all = "snake_case")]
+pub enum ArchiveSortField {
+    CreatedAt,
+    ArchiveName,
+    EncryptionRevision,
+    FileCount,
+}
+
+impl Listable for ArchiveListing {
+    type SortField = ArchiveSortField;
+
+    fn list_id(&self) -> &str {
+        &self.entry.archive_id
+    }
+
+    fn compare_by(&self, other: &Self, field: ArchiveSortField) -> Ordering {
+        let (a, b) = (&self.entry, &other.entry);
+        match field {
+            ArchiveSortField::CreatedAt => a.created_at.cmp(&b.created_at),
+            ArchiveSortField::ArchiveName => compare_text(&a.archive_name, &b.archive_name),
+            ArchiveSortField::EncryptionRevision => {
+                a.encryption_revision.cmp(&b.encryption_revision)
+            }
+            ArchiveSortField::FileCount => a.file_count.cmp(&b.file_count),
+        }
+    }
+}
+
+/// Input for listing a vault's archives
+#[derive(Debug, Deserialize, specta::Type)]
+pub struct ListArchivesRequest {
+    pub vault_id: String,
+    /// Oldest first when omitted
+    #[serde(default)]
+    pub sort: Option<SortSpec<ArchiveSortField>>,
+    #[serde(default)]
+    pub page: Option<PageRequest>,
+}
+
+input_rules! {
+    ListArchivesRequest {
+        vault_id("Vault ID"): [ExistingVaultId],
+    }
+}
+
+/// One page of a vault's archives
+#[derive(Debug, Serialize, specta::Type)]
+pub struct ListArchivesResponse {
+    pub archives: Vec<ArchiveListing>,
+    /// Archives in all pages
+    pub total_count: usize,
+}
+
+/// Input for marking an archive immutable or clearing the flag
+#[derive(Debug, Deserialize, specta::Type)]
+pub struct SetArchiveImmutableRequest {
+    pub vault_id: String,
+    pub archive_id: String,
+    pub immutable: bool,
+    /// Required to clear the flag: the user must type "UNLOCK"
+    pub confirmation: Option<String>,
+}
+
+input_rules! {
+    SetArchiveImmutableRequest {
+        vault_id("Vault ID"): [ExistingVaultId],
+        archive_id("Archive ID"): [NonEmptyId],
+    }
+}
+
+/// Input for repairing an archive from its parity
+#[derive(Debug, Deserialize, specta::Type)]
+pub struct RepairArchiveRequest {
+    pub vault_id: String,
+    pub archive_id: String,
+}
+
+input_rules! {
+    RepairArchiveRequest {
+        vault_id("Vault ID"): [ExistingVaultId],
+        archive_id("Archive ID"): [NonEmptyId],
+    }
+}
+
+/// Input for patching files of an archive
+#[derive(Debug, Deserialize, specta::Type)]
+pub struct PatchArchiveRequest {
+    pub vault_id: String,
+    pub archive_id: String,
+    /// Files to replace, each a path the archive's manifest lists
+    pub replacements: Vec<PatchReplacement>,
+}
+
+input_rules! {
+    PatchArchiveRequest {
+        vault_id("Vault ID"): [ExistingVaultId],
+        archive_id("Archive ID"): [NonEmptyId],
+    }
+}
+
+/// Search a vault's archive index
+#[tauri::command]
+#[specta::specta]
+#[instrument(skip_all, fields(vault_id = %input.vault_id))]
+pub async fn search_archives(
+    input: SearchArchivesRequest,
+) -> CommandResponse<SearchArchivesResponse> {
+    input.validate()?;
+
+    let manager = VaultManager::new();
+    manager
+        .search_archives(&input.vault_id, &input.query)
+        .await
+        .map(|matches| SearchArchivesResponse { matches })
+        .map_err(|e| archive_error(&input.vault_id, e))
+}
+
+/// Replace an archive's comment in the index and external manifest
+#[tauri::command]
+#[specta::specta]
+#[instrument(skip_all, fields(vault_id = %input.vault_id, archive_id = %input.archive_id))]
+pub async fn update_archive_comment(
+    input: UpdateArchiveCommentRequest,
+) -> CommandResponse<ArchiveIndexEntry> {
+    input.validate()?;
+
+    let manager = VaultManager::new();
+    manager
+        .update_archive_comment(&input.vault_id, &input.archive_id, input.comment.as_deref())
+        .await
+        .map_err(|e| archive_error(&input.vault_id, e))
+}
+
+/// List a vault's archives with their immutability and OS protection state
+#[tauri::command]
+#[specta::specta]
+#[instrument(skip_all, fields(vault_id = %input.vault_id))]
+pub async fn list_archives(input: ListArchivesRequest) -> CommandResponse<ListArchivesResponse> {
+    input.validate()?;
+
+    let manager = VaultManager::new();
+    manager
+        .list_archives(&input.vault_id)
+        .await
+        .map(|archives| {
+            let page = sort_and_paginate(
+                archives,
+                input.sort,
+                ArchiveSortField::CreatedAt,
+                input.page,
+            );
+            ListArchivesResponse {
+                archives: page.items,
+                total_count: page.total_count,
+            }
+        })
+        .map_err(|e| archive_error(&input.vault_id, e))
+}
+
+/// Mark an archive immutable so nothing writes over or removes it
+///
+/// Clearing the flag needs the typed confirmation. OS-level protection is
+/// applied where the platform allows; the response says whether it took.
+#[tauri::command]
+#[specta::specta]
+#[instrument(skip_all, fields(
+    vault_id = %input.vault_id,
+    archive_id = %input.archive_id,
+    immutable = input.immutable
+))]
+pub async fn set_archive_immutable(
+    input: SetArchiveImmutableRequest,
+) -> CommandResponse<ArchiveListing> {
+    input.validate()?;
+
+    let manager = VaultManager::new();
+    manager
+        .set_archive_immutable(
+            &input.vault_id,
+            &input.archive_id,
+            input.immutable,
+            input.confirmation.as_deref(),
+        )
+        .await
+        .map_err(|e| archive_error(&input.vault_id, e))
+}
+
+/// Check an archive against its parity sidecar and rebuild damaged blocks
+///
+/// The report lists the byte ranges rewritten, or says the damage is beyond
+/// what the parity covers, in which case the archive is left as it was.
+#[tauri::command]
+#[specta::specta]
+#[instrument(skip_all, fields(vault_id = %input.vault_id, archive_id = %input.archive_id))]
+pub async fn repair_archive(input: RepairArchiveRequest) -> CommandResponse<ArchiveRepairReport> {
+    input.validate()?;
+
+    // Nothing else may write the archive while it is rewritten
+    let _operation = begin_exclusive_operation(OperationKind::Maintenance)
+        .map_err(|e| Box::new(CommandError::from(e)))?;
+
+    let manager = VaultManager::new();
+    manager
+        .repair_archive(&input.vault_id, &input.archive_id)
+        .await
+        .map_err(|e| archive_error(&input.vault_id, e))
+}
+
+/// Replace a few small files of the vault's latest archive without
+/// re-encrypting it
+///
+/// The replacements are encrypted into a patch beside the archive, up to the
+/// configured size cap together. Decrypting the archive applies its patches,
+/// newest first.
+#[tauri::command]
+#[specta::specta]
+#[instrument(skip_all, fields(
+    vault_id = %input.vault_id,
+    archive_id = %input.archive_id,
+    replacement_count = input.replacements.len()
+))]
+pub async fn patch_archive(input: PatchArchiveRequest) -> CommandResponse<ArchivePatchEntry> {
+    input.validate()?;
+
+    // A patch is an encryption of its own
+    let _operation = begin_exclusive_operation(OperationKind::Encryption)
+        .map_err(|e| Box::new(CommandError::from(e)))?;
+
+    let manager = VaultManager::new();
+    manager
+        .patch_archive(&input.vault_id, &input.archive_id, &input.replacements)
+        .await
+        .map_err(|e| archive_error(&input.vault_id, e))
+}
+
+/// Set whether an archive name another vault already has in the output
+/// folder gets the vault's name added or fails the encryption
+#[tauri::command]
+#[specta::specta]
+#[instrument]
+pub async fn set_cross_vault_name_policy(policy: CrossVaultNamePolicy) -> CommandResponse<()> {
+    let save = AppConfig::load().and_then(|mut config| {
+        config.cross_vault_name_policy = policy;
+        config.save()
+    });
+    save.map_err(|e| {
+        Box::new(
+            CommandError::operation(
+                ErrorCode::StorageFailed,
+                format!("Failed to save archive naming policy: {}", e),
+            )
+            .with_recovery_guidance("Check that the config directory is writable"),
+        )
+    })
+}
+
+pub(super) fn archive_error(vault_id: &str, error: VaultError) -> Box<CommandError> {
+    match error {
+        VaultError::NotFound(_) => Box::new(CommandError::operation(
+            ErrorCode::VaultNotFound,
+            format!("Vault '{}' not found", vault_id),
+        )),
+        VaultError::InvalidOperation(msg) => Box::new(CommandError::validation(msg)),
+        e @ VaultError::ArchiveImmutable { .. } => Box::new(CommandError::operation(
+            ErrorCode::ArchiveImmutable,
+            e.to_string(),
+        )),
+        e => Box::new(
+            CommandError::operation(ErrorCode::StorageFailed, "Failed to access archive index")
+                .with_details(e.to_string()),
+        ),
+    }
+}
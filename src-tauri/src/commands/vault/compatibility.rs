@@ -0,0 +1,67 @@
+//! Compatibility changelog commands
+//!
+//! Lists vault-format changes (manifest fields, archive naming, compression,
+//! key registry) between app versions. On the first start after an upgrade
+//! that includes a high-impact change, a `compatibility-changes-detected`
+//! event is emitted so the UI can explain what changed.
+
+use crate::commands::command_types::{CommandError, CommandResponse, ErrorCode};
+use crate::services::key_management::yubikey::infrastructure::pty::app_handle::get_app_handle;
+use crate::services::vault::VaultManager;
+use crate::services::vault::domain::VaultError;
+use crate::services::vault::domain::models::CompatibilityReport;
+use serde::Deserialize;
+use tauri::Emitter;
+use tracing::{instrument, warn};
+
+/// Event emitted after an upgrade with high-impact changes, with a
+/// `CompatibilityReport` payload
+pub const COMPATIBILITY_CHANGES_EVENT: &str = "compatibility-changes-detected";
+
+/// Input for listing compatibility changes
+#[derive(Debug, Deserialize, specta::Type)]
+pub struct GetCompatibilityChangesRequest {
+    /// List changes after this version (default: the version that ran before
+    /// this one)
+    pub since_version: Option<String>,
+}
+
+/// Vault-format changes between `since_version` and this build
+#[tauri::command]
+#[specta::specta]
+#[instrument]
+pub async fn get_compatibility_changes(
+    input: GetCompatibilityChangesRequest,
+) -> CommandResponse<CompatibilityReport> {
+    VaultManager::new()
+        .get_compatibility_changes(input.since_version.as_deref())
+        .map_err(|e| match e {
+            VaultError::InvalidOperation(msg) => Box::new(CommandError::validation(msg)),
+            e => Box::new(
+                CommandError::operation(ErrorCode::StorageFailed, "Failed to load app config")
+                    .with_details(e.to_string()),
+            ),
+        })
+}
+
+/// Record this start and announce high-impact changes after an upgrade
+///
+/// Called once the app handle is available. Never fails startup; problems
+/// are only logged.
+pub fn record_app_start() {
+    let report = match VaultManager::new().record_app_start() {
+        Ok(Some(report)) if report.has_high_impact() => report,
+        Ok(_) => return,
+        Err(e) => {
+            warn!(error = %e, "Failed to record app version");
+            return;
+        }
+    };
+
+    let Some(app) = get_app_handle() else {
+        return;
+    };
+    if let Err(e) = app.emit(COMPATIBILITY_CHANGES_EVENT, &report) {
+        warn!(error = %e, "Failed to emit compatibility changes");
+    }
+}
@@ -0,0 +1,118 @@
+//! Dead man's switch commands
+//!
+//! Set up a vault's check-in schedule and lapsed message, see where it
+//! stands, and check in. A check-in needs the typed confirmation.
+
+use crate::commands::command_types::{CommandError, CommandResponse, ErrorCode, ValidateInput};
+use crate::commands::validation::{ExistingVaultId, input_rules};
+use crate::services::vault::VaultManager;
+use crate::services::vault::domain::VaultError;
+use crate::services::vault::domain::models::{DeadMansSwitchInput, DeadMansSwitchStatus};
+use serde::Deserialize;
+use tracing::instrument;
+
+/// Input for setting up, changing or removing a vault's dead man's switch
+#[derive(Debug, Deserialize, specta::Type)]
+pub struct SetDeadMansSwitchRequest {
+    pub vault_id: String,
+    /// Missing removes the switch; changing it keeps the last check-in
+    pub switch: Option<DeadMansSwitchInput>,
+}
+
+input_rules! {
+    SetDeadMansSwitchRequest {
+        vault_id("Vault ID"): [ExistingVaultId],
+    }
+}
+
+/// Input for reading a vault's dead man's switch
+#[derive(Debug, Deserialize, specta::Type)]
+pub struct GetDeadMansSwitchStatusRequest {
+    pub vault_id: String,
+}
+
+input_rules! {
+    GetDeadMansSwitchStatusRequest {
+        vault_id("Vault ID"): [ExistingVaultId],
+    }
+}
+
+/// Input for checking in
+#[derive(Debug, Deserialize, specta::Type)]
+pub struct CheckInRequest {
+    pub vault_id: String,
+    /// The user must type "CHECK IN"
+    pub confirmation: Option<String>,
+}
+
+input_rules! {
+    CheckInRequest {
+        vault_id("Vault ID"): [ExistingVaultId],
+    }
+}
+
+/// Set up, change or remove a vault's dead man's switch
+#[tauri::command]
+#[specta::specta]
+#[instrument(skip_all, fields(vault_id = %input.vault_id))]
+pub async fn set_dead_mans_switch(
+    input: SetDeadMansSwitchRequest,
+) -> CommandResponse<Option<DeadMansSwitchStatus>> {
+    input.validate()?;
+
+    let manager = VaultManager::new();
+    manager
+        .set_dead_mans_switch(&input.vault_id, input.switch)
+        .await
+        .map_err(|e| dead_mans_switch_error(&input.vault_id, e))
+}
+
+/// Where a vault's dead man's switch stands (none if never set up)
+///
+/// The lapsed message is only included once the interval has lapsed, and the
+/// state is `unknown` while the system clock is implausible.
+#[tauri::command]
+#[specta::specta]
+#[instrument(skip_all, fields(vault_id = %input.vault_id))]
+pub async fn get_dead_mans_switch_status(
+    input: GetDeadMansSwitchStatusRequest,
+) -> CommandResponse<Option<DeadMansSwitchStatus>> {
+    input.validate()?;
+
+    let manager = VaultManager::new();
+    manager
+        .get_dead_mans_switch_status(&input.vault_id)
+        .await
+        .map_err(|e| dead_mans_switch_error(&input.vault_id, e))
+}
+
+/// Restart a vault's check-in interval from now
+#[tauri::command]
+#[specta::specta]
+#[instrument(skip_all, fields(vault_id = %input.vault_id))]
+pub async fn check_in(input: CheckInRequest) -> CommandResponse<DeadMansSwitchStatus> {
+    input.validate()?;
+
+    let manager = VaultManager::new();
+    manager
+        .check_in(&input.vault_id, input.confirmation.as_deref())
+        .await
+        .map_err(|e| dead_mans_switch_error(&input.vault_id, e))
+}
+
+fn dead_mans_switch_error(vault_id: &str, error: VaultError) -> Box<CommandError> {
+    match error {
+        VaultError::NotFound(_) => Box::new(CommandError::operation(
+            ErrorCode::VaultNotFound,
+            format!("Vault '{}' not found", vault_id),
+        )),
+        VaultError::InvalidOperation(msg) => Box::new(CommandError::validation(msg)),
+        e => Box::new(
+            CommandError::operation(
+                ErrorCode::StorageFailed,
+                "Failed to access the dead man's switch",
+            )
+            .with_details(e.to_string()),
+        ),
+    }
+}
@@ -0,0 +1,96 @@
+//! Directory comparison commands
+//!
+//! Check whether a local folder is already backed up by comparing it against
+//! a vault's manifest. Nothing is decrypted; local files are hashed and
+//! matched against the recorded SHA-256 of each vault file.
+//!
+//! Experimental: gated on `FeatureFlag::DirectoryComparison`.
+
+use crate::commands::command_types::{CommandError, CommandResponse, ErrorCode, ValidateInput};
+use crate::commands::validation::{
+    ExistingDirectory, ExistingVaultId, MaxLength, NonEmptyId, input_rules,
+};
+use crate::constants::MAX_OPERATION_ID_LENGTH;
+use crate::features::{FeatureFlag, require_feature};
+use crate::services::vault::VaultManager;
+use crate::services::vault::domain::VaultError;
+use crate::services::vault::domain::models::DirectoryComparison;
+use serde::Deserialize;
+use std::path::Path;
+use tracing::instrument;
+
+/// Input for comparing a directory against a vault
+#[derive(Debug, Deserialize, specta::Type)]
+pub struct CompareVaultToDirectoryRequest {
+    pub vault_id: String,
+    /// Archive to compare against; must be the vault's latest
+    #[serde(default)]
+    pub archive_id: Option<String>,
+    pub directory: String,
+    /// Glob patterns to skip, on top of the vault template's exclusions
+    #[serde(default)]
+    pub exclude_patterns: Vec<String>,
+    /// Progress stream to report under; generated when omitted
+    #[serde(default)]
+    pub operation_id: Option<String>,
+}
+
+input_rules! {
+    CompareVaultToDirectoryRequest {
+        vault_id("Vault ID"): [ExistingVaultId],
+        archive_id("Archive ID"): [NonEmptyId],
+        directory("Directory"): [ExistingDirectory],
+        operation_id("Operation ID"): [NonEmptyId, MaxLength { max: MAX_OPERATION_ID_LENGTH }],
+    }
+}
+
+/// Compare a local directory against a vault's file list
+///
+/// Files are sorted into covered, modified, missing from the vault, and in
+/// the vault but not local. Hashing progress is published under the
+/// operation ID and can be polled with `get_progress`.
+#[tauri::command]
+#[specta::specta]
+#[instrument(skip_all, fields(vault_id = %input.vault_id))]
+pub async fn compare_vault_to_directory(
+    input: CompareVaultToDirectoryRequest,
+) -> CommandResponse<DirectoryComparison> {
+    require_feature(FeatureFlag::DirectoryComparison)?;
+    input.validate()?;
+
+    let operation_id = input
+        .operation_id
+        .unwrap_or_else(|| format!("directory_comparison_{}", chrono::Utc::now().timestamp()));
+
+    let manager = VaultManager::new();
+    manager
+        .compare_vault_to_directory(
+            &input.vault_id,
+            input.archive_id.as_deref(),
+            Path::new(&input.directory),
+            &input.exclude_patterns,
+            &operation_id,
+        )
+        .await
+        .map_err(|e| comparison_error(&input.vault_id, e))
+}
+
+fn comparison_error(vault_id: &str, error: VaultError) -> Box<CommandError> {
+    match error {
+        VaultError::NotFound(msg) => Box::new(
+            CommandError::operation(
+                ErrorCode::VaultNotFound,
+                format!("Vault '{}' or its archive was not found", vault_id),
+            )
+            .with_details(msg),
+        ),
+        VaultError::InvalidOperation(msg) => Box::new(CommandError::validation(msg)),
+        e => Box::new(
+            CommandError::operation(
+                ErrorCode::StorageFailed,
+                "Failed to compare the directory with the vault",
+            )
+            .with_details(e.to_string()),
+        ),
+    }
+}
@@ -0,0 +1,43 @@
+//! Removable media commands
+//!
+//! Encryption to a USB drive or SD card reports `safe_to_eject` for the
+//! bundles it wrote. Before the user unplugs the drive, `prepare_eject`
+//! flushes everything the app wrote there since, such as upload checksums
+//! and parity, and says whether anything is still running.
+
+use crate::commands::command_types::{CommandError, CommandResponse, ErrorCode, ValidateInput};
+use crate::commands::validation::{NonEmpty, input_rules};
+use crate::logging::spawn_blocking;
+use crate::services::vault::VaultManager;
+use crate::services::vault::domain::models::EjectPreparation;
+use serde::Deserialize;
+use tracing::instrument;
+
+/// Input for preparing a volume to be ejected
+#[derive(Debug, Deserialize, specta::Type)]
+pub struct PrepareEjectRequest {
+    /// Volume name or mount point, as reported by encryption
+    pub volume: String,
+}
+
+input_rules! {
+    PrepareEjectRequest {
+        volume("Volume"): [NonEmpty],
+    }
+}
+
+/// Flush pending writes to a removable volume and report when it's safe to
+/// eject
+#[tauri::command]
+#[specta::specta]
+#[instrument(skip_all, fields(volume = %input.volume))]
+pub async fn prepare_eject(input: PrepareEjectRequest) -> CommandResponse<EjectPreparation> {
+    input.validate()?;
+
+    let preparation = spawn_blocking(move || VaultManager::new().prepare_eject(&input.volume))
+        .await
+        .map_err(|e| {
+            CommandError::operation(ErrorCode::InternalError, format!("Task error: {e}"))
+        })?;
+    Ok(preparation)
+}
@@ -0,0 +1,65 @@
+//! Cross-archive file search commands
+//!
+//! Find which archives hold a file, across every vault, from the file lists
+//! recorded at encryption time. Nothing is decrypted.
+
+use crate::commands::command_types::{CommandError, CommandResponse, ErrorCode, ValidateInput};
+use crate::commands::validation::{MaxLength, NonEmpty, input_rules};
+use crate::services::vault::VaultManager;
+use crate::services::vault::domain::VaultError;
+use crate::services::vault::domain::models::{FileSearchResults, FileSearchScope};
+use serde::Deserialize;
+use tracing::instrument;
+
+/// Longest accepted search query, in characters
+const MAX_QUERY_LENGTH: usize = 500;
+
+/// Input for searching files across archives
+#[derive(Debug, Deserialize, specta::Type)]
+pub struct SearchFilesRequest {
+    /// Words, "quoted phrases", `ext:pdf`, and `size>10mb` style filters,
+    /// all of which must match
+    pub query: String,
+    /// Vaults to search; all vaults when missing
+    pub scope: Option<FileSearchScope>,
+}
+
+input_rules! {
+    SearchFilesRequest {
+        query("Search query"): [NonEmpty, MaxLength { max: MAX_QUERY_LENGTH }],
+    }
+}
+
+/// Search every indexed archive for matching files
+///
+/// Results are grouped by vault and archive, newest archive first.
+#[tauri::command]
+#[specta::specta]
+#[instrument(skip_all, fields(query_len = input.query.chars().count()))]
+pub async fn search_files(input: SearchFilesRequest) -> CommandResponse<FileSearchResults> {
+    input.validate()?;
+
+    let scope = input.scope.unwrap_or(FileSearchScope::AllVaults);
+    let manager = VaultManager::new();
+    manager
+        .search_files(&input.query, &scope)
+        .await
+        .map_err(search_error)
+}
+
+fn search_error(error: VaultError) -> Box<CommandError> {
+    match error {
+        e @ VaultError::NotFound(_) => Box::new(CommandError::operation(
+            ErrorCode::VaultNotFound,
+            e.to_string(),
+        )),
+        VaultError::InvalidOperation(msg) => Box::new(
+            CommandError::validation(msg)
+                .with_recovery_guidance("Try ext:pdf, size>10mb, or a \"quoted phrase\""),
+        ),
+        e => Box::new(
+            CommandError::operation(ErrorCode::StorageFailed, "Failed to search archive files")
+                .with_details(e.to_string()),
+        ),
+    }
+}
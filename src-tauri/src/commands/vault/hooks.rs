@@ -0,0 +1,126 @@
+//! Vault hook commands
+//!
+//! Commands for configuring the programs a vault runs after an operation
+//! (e.g. copy a new archive to a NAS) and for trying one out.
+
+use crate::commands::command_types::{CommandError, CommandResponse, ErrorCode, ValidateInput};
+use crate::commands::validation::{ExistingVaultId, NonEmptyId, input_rules};
+use crate::services::vault::VaultManager;
+use crate::services::vault::domain::VaultError;
+use crate::services::vault::domain::models::{HookEvent, VaultHooks};
+use crate::services::vault::infrastructure::HookRunOutcome;
+use serde::Deserialize;
+use tracing::instrument;
+
+/// Input for reading a vault's hooks
+#[derive(Debug, Deserialize, specta::Type)]
+pub struct GetVaultHooksRequest {
+    pub vault_id: String,
+}
+
+input_rules! {
+    GetVaultHooksRequest {
+        vault_id("Vault ID"): [ExistingVaultId],
+    }
+}
+
+/// Input for replacing a vault's hooks
+#[derive(Debug, Deserialize, specta::Type)]
+pub struct UpdateVaultHooksRequest {
+    pub vault_id: String,
+    pub hooks: VaultHooks,
+}
+
+input_rules! {
+    UpdateVaultHooksRequest {
+        vault_id("Vault ID"): [ExistingVaultId],
+    }
+}
+
+/// Input for trying out one hook
+#[derive(Debug, Deserialize, specta::Type)]
+pub struct TestHookRequest {
+    pub vault_id: String,
+    pub hook_id: String,
+}
+
+input_rules! {
+    TestHookRequest {
+        vault_id("Vault ID"): [ExistingVaultId],
+        hook_id("Hook ID"): [NonEmptyId],
+    }
+}
+
+/// Get a vault's post-operation hooks
+#[tauri::command]
+#[specta::specta]
+#[instrument(skip_all, fields(vault_id = %input.vault_id))]
+pub async fn get_vault_hooks(input: GetVaultHooksRequest) -> CommandResponse<VaultHooks> {
+    input.validate()?;
+
+    let manager = VaultManager::new();
+    manager
+        .get_vault_hooks(&input.vault_id)
+        .await
+        .map_err(|e| hooks_error(&input.vault_id, e))
+}
+
+/// Replace a vault's post-operation hooks
+///
+/// Every executable must be an absolute path to an executable file, and
+/// arguments may only use the known placeholders.
+#[tauri::command]
+#[specta::specta]
+#[instrument(skip_all, fields(vault_id = %input.vault_id))]
+pub async fn update_vault_hooks(input: UpdateVaultHooksRequest) -> CommandResponse<VaultHooks> {
+    input.validate()?;
+
+    let manager = VaultManager::new();
+    manager
+        .update_vault_hooks(&input.vault_id, input.hooks)
+        .await
+        .map_err(|e| hooks_error(&input.vault_id, e))
+}
+
+/// Run one hook now with dummy values and report how it went
+#[tauri::command]
+#[specta::specta]
+#[instrument(skip_all, fields(vault_id = %input.vault_id, hook_id = %input.hook_id))]
+pub async fn test_hook(input: TestHookRequest) -> CommandResponse<HookRunOutcome> {
+    input.validate()?;
+
+    let manager = VaultManager::new();
+    manager
+        .test_hook(&input.vault_id, &input.hook_id)
+        .await
+        .map_err(|e| hooks_error(&input.vault_id, e))
+}
+
+/// Run a vault's hooks after an operation finished
+///
+/// Waits at most the vault's response grace period and never fails the
+/// calling command; hook outcomes go to the operation log.
+pub async fn run_operation_hooks(
+    event: HookEvent,
+    vault_id: &str,
+    archive_path: &str,
+    status: &str,
+) {
+    VaultManager::new()
+        .run_vault_hooks(event, vault_id, archive_path, status)
+        .await
+}
+
+fn hooks_error(vault_id: &str, error: VaultError) -> Box<CommandError> {
+    match error {
+        VaultError::NotFound(_) => Box::new(CommandError::operation(
+            ErrorCode::VaultNotFound,
+            format!("Vault '{}' not found", vault_id),
+        )),
+        VaultError::InvalidOperation(msg) => Box::new(CommandError::validation(msg)),
+        e => Box::new(
+            CommandError::operation(ErrorCode::StorageFailed, "Failed to access vault hooks")
+                .with_details(e.to_string()),
+        ),
+    }
+}
@@ -0,0 +1,78 @@
+//! Inventory export commands
+//!
+//! Write a vault's file listing (paths, sizes, linked items, optionally
+//! SHA-256 hashes) as CSV or JSON, for someone who needs to know what a vault
+//! holds without receiving the data. Nothing is decrypted.
+
+use crate::commands::command_types::{CommandError, CommandResponse, ErrorCode, ValidateInput};
+use crate::commands::validation::{ExistingVaultId, NonEmpty, NonEmptyId, input_rules};
+use crate::services::vault::VaultManager;
+use crate::services::vault::domain::VaultError;
+use crate::services::vault::domain::models::{InventoryExportResult, InventoryFormat};
+use serde::Deserialize;
+use std::path::Path;
+use tracing::instrument;
+
+/// Input for exporting a vault inventory
+#[derive(Debug, Deserialize, specta::Type)]
+pub struct ExportInventoryRequest {
+    pub vault_id: String,
+    pub format: InventoryFormat,
+    /// Absolute path of the file to write
+    pub output_path: String,
+    #[serde(default)]
+    pub include_hashes: bool,
+    /// Archive to list; must be the vault's latest
+    #[serde(default)]
+    pub archive_id: Option<String>,
+}
+
+input_rules! {
+    ExportInventoryRequest {
+        vault_id("Vault ID"): [ExistingVaultId],
+        output_path("Output path"): [NonEmpty],
+        archive_id("Archive ID"): [NonEmptyId],
+    }
+}
+
+/// Export a vault's file listing
+///
+/// CSV output starts with a UTF-8 BOM so spreadsheet apps read non-ASCII
+/// names correctly. The export is recorded in the operation log.
+#[tauri::command]
+#[specta::specta]
+#[instrument(skip_all, fields(vault_id = %input.vault_id, format = ?input.format))]
+pub async fn export_inventory(
+    input: ExportInventoryRequest,
+) -> CommandResponse<InventoryExportResult> {
+    input.validate()?;
+
+    let manager = VaultManager::new();
+    manager
+        .export_inventory(
+            &input.vault_id,
+            input.archive_id.as_deref(),
+            input.format,
+            Path::new(&input.output_path),
+            input.include_hashes,
+        )
+        .await
+        .map_err(|e| inventory_error(&input.vault_id, e))
+}
+
+fn inventory_error(vault_id: &str, error: VaultError) -> Box<CommandError> {
+    match error {
+        VaultError::NotFound(msg) => Box::new(
+            CommandError::operation(
+                ErrorCode::VaultNotFound,
+                format!("Vault '{}' or its archive was not found", vault_id),
+            )
+            .with_details(msg),
+        ),
+        VaultError::InvalidOperation(msg) => Box::new(CommandError::validation(msg)),
+        e => Box::new(
+            CommandError::operation(ErrorCode::StorageFailed, "Failed to export the inventory")
+                .with_details(e.to_string()),
+        ),
+    }
+}
@@ -0,0 +1,147 @@
+//! Vault item commands
+//!
+//! Structured inheritance checklist items ("wallet descriptor", "lawyer
+//! contact") stored in the vault manifest, each optionally linked to
+//! archived files or indexed archives.
+
+use crate::commands::command_types::{CommandError, CommandResponse, ErrorCode, ValidateInput};
+use crate::commands::validation::{ExistingVaultId, NonEmptyId, input_rules};
+use crate::services::vault::VaultManager;
+use crate::services::vault::domain::VaultError;
+use crate::services::vault::domain::models::{VaultItem, VaultItemInput, VaultItemView};
+use serde::{Deserialize, Serialize};
+use tracing::instrument;
+
+/// Input for adding an item to a vault
+#[derive(Debug, Deserialize, specta::Type)]
+pub struct AddVaultItemRequest {
+    pub vault_id: String,
+    pub item: VaultItemInput,
+}
+
+input_rules! {
+    AddVaultItemRequest {
+        vault_id("Vault ID"): [ExistingVaultId],
+    }
+}
+
+/// Input for editing an item
+#[derive(Debug, Deserialize, specta::Type)]
+pub struct UpdateVaultItemRequest {
+    pub vault_id: String,
+    pub item_id: String,
+    /// Replaces all editable fields
+    pub item: VaultItemInput,
+}
+
+input_rules! {
+    UpdateVaultItemRequest {
+        vault_id("Vault ID"): [ExistingVaultId],
+        item_id("Item ID"): [NonEmptyId],
+    }
+}
+
+/// Input for removing an item
+#[derive(Debug, Deserialize, specta::Type)]
+pub struct RemoveVaultItemRequest {
+    pub vault_id: String,
+    pub item_id: String,
+}
+
+input_rules! {
+    RemoveVaultItemRequest {
+        vault_id("Vault ID"): [ExistingVaultId],
+        item_id("Item ID"): [NonEmptyId],
+    }
+}
+
+/// Input for listing a vault's items
+#[derive(Debug, Deserialize, specta::Type)]
+pub struct ListVaultItemsRequest {
+    pub vault_id: String,
+}
+
+input_rules! {
+    ListVaultItemsRequest {
+        vault_id("Vault ID"): [ExistingVaultId],
+    }
+}
+
+/// A vault's items with their unresolved links
+#[derive(Debug, Serialize, specta::Type)]
+pub struct ListVaultItemsResponse {
+    pub items: Vec<VaultItemView>,
+}
+
+/// Add an item; linked paths must exist in the latest archive's manifest
+#[tauri::command]
+#[specta::specta]
+#[instrument(skip_all, fields(vault_id = %input.vault_id))]
+pub async fn add_vault_item(input: AddVaultItemRequest) -> CommandResponse<VaultItem> {
+    input.validate()?;
+
+    let manager = VaultManager::new();
+    manager
+        .add_vault_item(&input.vault_id, input.item)
+        .await
+        .map_err(|e| item_error(&input.vault_id, e))
+}
+
+/// Replace an item's category, title, links, note and completion
+#[tauri::command]
+#[specta::specta]
+#[instrument(skip_all, fields(vault_id = %input.vault_id, item_id = %input.item_id))]
+pub async fn update_vault_item(input: UpdateVaultItemRequest) -> CommandResponse<VaultItem> {
+    input.validate()?;
+
+    let manager = VaultManager::new();
+    manager
+        .update_vault_item(&input.vault_id, &input.item_id, input.item)
+        .await
+        .map_err(|e| item_error(&input.vault_id, e))
+}
+
+/// Remove an item, returning it
+#[tauri::command]
+#[specta::specta]
+#[instrument(skip_all, fields(vault_id = %input.vault_id, item_id = %input.item_id))]
+pub async fn remove_vault_item(input: RemoveVaultItemRequest) -> CommandResponse<VaultItem> {
+    input.validate()?;
+
+    let manager = VaultManager::new();
+    manager
+        .remove_vault_item(&input.vault_id, &input.item_id)
+        .await
+        .map_err(|e| item_error(&input.vault_id, e))
+}
+
+/// List a vault's items, flagging links that disappeared in a later backup
+#[tauri::command]
+#[specta::specta]
+#[instrument(skip_all, fields(vault_id = %input.vault_id))]
+pub async fn list_vault_items(
+    input: ListVaultItemsRequest,
+) -> CommandResponse<ListVaultItemsResponse> {
+    input.validate()?;
+
+    let manager = VaultManager::new();
+    manager
+        .list_vault_items(&input.vault_id)
+        .await
+        .map(|items| ListVaultItemsResponse { items })
+        .map_err(|e| item_error(&input.vault_id, e))
+}
+
+fn item_error(vault_id: &str, error: VaultError) -> Box<CommandError> {
+    match error {
+        VaultError::NotFound(_) => Box::new(CommandError::operation(
+            ErrorCode::VaultNotFound,
+            format!("Vault '{}' not found", vault_id),
+        )),
+        VaultError::InvalidOperation(msg) => Box::new(CommandError::validation(msg)),
+        e => Box::new(
+            CommandError::operation(ErrorCode::StorageFailed, "Failed to update vault items")
+                .with_details(e.to_string()),
+        ),
+    }
+}
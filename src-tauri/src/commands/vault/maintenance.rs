@@ -0,0 +1,135 @@
+//! Bulk vault maintenance commands
+//!
+//! Run statistics refresh, integrity verification, manifest migration, and
+//! registry reconciliation across many vaults at once, and fetch the report
+//! from the last run. Also quarantines archives left incomplete by an
+//! interrupted encryption and purges old quarantined files.
+
+use crate::commands::command_types::{CommandError, CommandResponse, ErrorCode, ValidateInput};
+use crate::commands::validation::{ExistingVaultId, input_rules};
+use crate::services::shared::infrastructure::progress::{
+    OperationKind, begin_exclusive_operation, begin_operation,
+};
+use crate::services::vault::VaultManager;
+use crate::services::vault::domain::VaultError;
+use crate::services::vault::domain::models::{
+    IncompleteArchiveReport, MaintenanceReport, MaintenanceScope, MaintenanceTask,
+    QuarantinePurgeReport,
+};
+use serde::Deserialize;
+use tracing::instrument;
+
+/// Input for a maintenance run
+#[derive(Debug, Deserialize, specta::Type)]
+pub struct RunMaintenanceRequest {
+    pub scope: MaintenanceScope,
+    pub tasks: Vec<MaintenanceTask>,
+    /// Progress stream to report under; generated when omitted
+    #[serde(default)]
+    pub operation_id: Option<String>,
+}
+
+/// Run maintenance tasks across vaults and return the combined report
+///
+/// Progress is published per task per vault under the operation ID and can
+/// be polled with `get_progress`.
+#[tauri::command]
+#[specta::specta]
+#[instrument(skip_all, fields(task_count = input.tasks.len()))]
+pub async fn run_maintenance(input: RunMaintenanceRequest) -> CommandResponse<MaintenanceReport> {
+    let _operation =
+        begin_operation(OperationKind::Maintenance).map_err(|e| Box::new(CommandError::from(e)))?;
+
+    let operation_id = input
+        .operation_id
+        .unwrap_or_else(|| format!("maintenance_{}", chrono::Utc::now().timestamp()));
+
+    let manager = VaultManager::new();
+    manager
+        .run_maintenance(&operation_id, input.scope, input.tasks)
+        .await
+        .map_err(maintenance_error)
+}
+
+/// Report from the most recent maintenance run, if any
+#[tauri::command]
+#[specta::specta]
+#[instrument]
+pub async fn get_last_maintenance_report() -> CommandResponse<Option<MaintenanceReport>> {
+    let manager = VaultManager::new();
+    manager
+        .get_last_maintenance_report()
+        .map_err(maintenance_error)
+}
+
+/// Input for purging a vault's quarantine
+#[derive(Debug, Deserialize, specta::Type)]
+pub struct PurgeQuarantineRequest {
+    pub vault_id: String,
+    /// Only delete files quarantined at least this many days ago
+    pub older_than_days: u32,
+}
+
+input_rules! {
+    PurgeQuarantineRequest {
+        vault_id("Vault ID"): [ExistingVaultId],
+    }
+}
+
+/// Find a vault's incomplete archives and move them into quarantine
+///
+/// Covers staged files never committed, leftovers from an interrupted
+/// commit, and archives that are empty or have an unreadable header. The
+/// same check runs for every vault at startup. Refused while an encryption
+/// or other operation is running, since its staged files would look
+/// incomplete.
+#[tauri::command]
+#[specta::specta]
+#[instrument]
+pub async fn scan_for_incomplete_archives(
+    vault_id: String,
+) -> CommandResponse<IncompleteArchiveReport> {
+    let _operation = begin_exclusive_operation(OperationKind::Maintenance)
+        .map_err(|e| Box::new(CommandError::from(e)))?;
+
+    let manager = VaultManager::new();
+    manager
+        .scan_for_incomplete_archives(&vault_id)
+        .await
+        .map_err(maintenance_error)
+}
+
+/// Delete quarantined files older than the given number of days
+///
+/// Quarantined files are never deleted any other way.
+#[tauri::command]
+#[specta::specta]
+#[instrument(skip_all, fields(vault_id = %input.vault_id))]
+pub async fn purge_quarantine(
+    input: PurgeQuarantineRequest,
+) -> CommandResponse<QuarantinePurgeReport> {
+    input.validate()?;
+
+    let _operation = begin_exclusive_operation(OperationKind::Maintenance)
+        .map_err(|e| Box::new(CommandError::from(e)))?;
+
+    let manager = VaultManager::new();
+    manager
+        .purge_quarantine(&input.vault_id, input.older_than_days)
+        .await
+        .map_err(maintenance_error)
+}
+
+fn maintenance_error(error: VaultError) -> Box<CommandError> {
+    match error {
+        VaultError::NotFound(msg) => Box::new(
+            CommandError::operation(ErrorCode::VaultNotFound, "A selected vault was not found")
+                .with_details(msg),
+        ),
+        VaultError::InvalidOperation(msg) => Box::new(CommandError::validation(msg)),
+        e => Box::new(
+            CommandError::operation(ErrorCode::StorageFailed, "Vault maintenance failed")
+                .with_details(e.to_string()),
+        ),
+    }
+}
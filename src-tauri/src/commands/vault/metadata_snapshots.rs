@@ -0,0 +1,80 @@
+//! Metadata snapshot commands
+//!
+//! Browse the restore points taken before key, vault and archive index
+//! changes, see what changed since one, and restore it. Snapshots hold the
+//! key registry, vault manifests and archive index, never private keys.
+
+use crate::commands::command_types::{CommandError, CommandResponse, ErrorCode, ValidateInput};
+use crate::commands::validation::{NonEmptyId, input_rules};
+use crate::services::vault::VaultManager;
+use crate::services::vault::domain::VaultError;
+use crate::services::vault::domain::models::{
+    MetadataRestoreResult, MetadataSnapshotDiff, MetadataSnapshotSummary,
+};
+use serde::Deserialize;
+use tracing::instrument;
+
+/// Input for restoring a metadata snapshot
+#[derive(Debug, Deserialize, specta::Type)]
+pub struct RestoreMetadataSnapshotRequest {
+    pub snapshot_id: String,
+    /// The user must type "RESTORE"
+    pub confirmation: Option<String>,
+}
+
+input_rules! {
+    RestoreMetadataSnapshotRequest {
+        snapshot_id("Snapshot ID"): [NonEmptyId],
+    }
+}
+
+/// List metadata restore points, newest first
+#[tauri::command]
+#[specta::specta]
+#[instrument]
+pub async fn list_metadata_snapshots() -> CommandResponse<Vec<MetadataSnapshotSummary>> {
+    VaultManager::new()
+        .list_metadata_snapshots()
+        .map_err(snapshot_error)
+}
+
+/// Describe what changed in keys, vaults and archives since a restore point
+#[tauri::command]
+#[specta::specta]
+#[instrument]
+pub async fn diff_metadata_snapshot(snapshot_id: String) -> CommandResponse<MetadataSnapshotDiff> {
+    VaultManager::new()
+        .diff_metadata_snapshot(&snapshot_id)
+        .map_err(snapshot_error)
+}
+
+/// Restore a metadata snapshot
+///
+/// Needs the typed confirmation. The replaced state is kept as a restore
+/// point, so a restore can itself be undone.
+#[tauri::command]
+#[specta::specta]
+#[instrument(skip_all, fields(snapshot_id = %input.snapshot_id))]
+pub async fn restore_metadata_snapshot(
+    input: RestoreMetadataSnapshotRequest,
+) -> CommandResponse<MetadataRestoreResult> {
+    input.validate()?;
+
+    VaultManager::new()
+        .restore_metadata_snapshot(&input.snapshot_id, input.confirmation.as_deref())
+        .map_err(snapshot_error)
+}
+
+fn snapshot_error(error: VaultError) -> Box<CommandError> {
+    match error {
+        VaultError::NotFound(msg) => Box::new(
+            CommandError::operation(ErrorCode::OperationNotFound, "Snapshot not found")
+                .with_details(msg),
+        ),
+        VaultError::InvalidOperation(msg) => Box::new(CommandError::validation(msg)),
+        e => Box::new(
+            CommandError::operation(ErrorCode::StorageFailed, "Metadata snapshot failed")
+                .with_details(e.to_string()),
+        ),
+    }
+}
@@ -3,8 +3,52 @@
 //! This module provides Tauri commands for managing vaults.
 //! For key operations, see commands::key_management.
 
+pub mod archives;
+pub mod compatibility;
+pub mod dead_mans_switch;
+pub mod directory_comparison;
+pub mod eject;
+pub mod file_search;
+pub mod hooks;
+pub mod inventory;
+pub mod items;
+pub mod maintenance;
+pub mod metadata_snapshots;
+pub mod notifications;
+pub mod onboarding;
+pub mod operation_log;
+pub mod retention;
+pub mod risk;
+pub mod shared_store;
+pub mod shutdown;
 pub mod statistics;
+pub mod statistics_history;
+pub mod templates;
+pub mod transforms;
+pub mod vault_conflict;
 pub mod vault_management;
 
+pub use archives::*;
+pub use compatibility::*;
+pub use dead_mans_switch::*;
+pub use directory_comparison::*;
+pub use eject::*;
+pub use file_search::*;
+pub use hooks::*;
+pub use inventory::*;
+pub use items::*;
+pub use maintenance::*;
+pub use metadata_snapshots::*;
+pub use notifications::*;
+pub use onboarding::*;
+pub use operation_log::*;
+pub use retention::*;
+pub use risk::*;
+pub use shared_store::*;
+pub use shutdown::*;
 pub use statistics::*;
+pub use statistics_history::*;
+pub use templates::*;
+pub use transforms::*;
+pub use vault_conflict::*;
 pub use vault_management::*;
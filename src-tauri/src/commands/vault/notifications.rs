@@ -0,0 +1,158 @@
+//! Vault notification commands
+//!
+//! Commands for the notifications digest (stale backups, pending changes,
+//! verification reminders), snoozing notifications, and per-vault
+//! notification preferences.
+
+use crate::commands::command_types::{CommandError, CommandResponse, ErrorCode, ValidateInput};
+use crate::commands::validation::{ExistingVaultId, InRange, NonEmptyId, input_rules};
+use crate::services::vault::VaultManager;
+use crate::services::vault::application::services::{DEFAULT_SNOOZE_DAYS, MAX_SNOOZE_DAYS};
+use crate::services::vault::domain::VaultError;
+use crate::services::vault::domain::models::{NotificationPreferences, VaultNotification};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tracing::instrument;
+
+/// Response containing the notifications digest
+#[derive(Debug, Serialize, specta::Type)]
+pub struct GetNotificationsResponse {
+    /// Highest priority first
+    pub notifications: Vec<VaultNotification>,
+}
+
+/// Input for dismissing a notification
+#[derive(Debug, Deserialize, specta::Type)]
+pub struct DismissNotificationRequest {
+    pub notification_id: String,
+    /// Days until the notification may reappear (default 7, max 365)
+    pub snooze_days: Option<u32>,
+}
+
+input_rules! {
+    DismissNotificationRequest {
+        notification_id("Notification ID"): [NonEmptyId],
+        snooze_days("Snooze"): [InRange { min: 1, max: MAX_SNOOZE_DAYS }],
+    }
+}
+
+/// Response after dismissing a notification
+#[derive(Debug, Serialize, specta::Type)]
+pub struct DismissNotificationResponse {
+    pub notification_id: String,
+    pub snoozed_until: DateTime<Utc>,
+}
+
+/// Input for reading a vault's notification preferences
+#[derive(Debug, Deserialize, specta::Type)]
+pub struct GetNotificationPreferencesRequest {
+    pub vault_id: String,
+}
+
+input_rules! {
+    GetNotificationPreferencesRequest {
+        vault_id("Vault ID"): [ExistingVaultId],
+    }
+}
+
+/// Input for replacing a vault's notification preferences
+#[derive(Debug, Deserialize, specta::Type)]
+pub struct UpdateNotificationPreferencesRequest {
+    pub vault_id: String,
+    pub preferences: NotificationPreferences,
+}
+
+input_rules! {
+    UpdateNotificationPreferencesRequest {
+        vault_id("Vault ID"): [ExistingVaultId],
+    }
+}
+
+/// Get the notifications digest across all vaults
+#[tauri::command]
+#[specta::specta]
+#[instrument]
+pub async fn get_notifications() -> CommandResponse<GetNotificationsResponse> {
+    let manager = VaultManager::new();
+
+    match manager.get_notifications() {
+        Ok(notifications) => Ok(GetNotificationsResponse { notifications }),
+        Err(e) => Err(Box::new(
+            CommandError::operation(ErrorCode::StorageFailed, "Failed to evaluate notifications")
+                .with_details(e.to_string()),
+        )),
+    }
+}
+
+/// Dismiss a notification so it doesn't reappear until the snooze expires
+#[tauri::command]
+#[specta::specta]
+#[instrument(skip_all, fields(notification_id = %input.notification_id))]
+pub async fn dismiss_notification(
+    input: DismissNotificationRequest,
+) -> CommandResponse<DismissNotificationResponse> {
+    input.validate()?;
+    let snooze_days = input.snooze_days.unwrap_or(DEFAULT_SNOOZE_DAYS);
+
+    let manager = VaultManager::new();
+
+    match manager.dismiss_notification(&input.notification_id, snooze_days) {
+        Ok(snoozed_until) => Ok(DismissNotificationResponse {
+            notification_id: input.notification_id,
+            snoozed_until,
+        }),
+        Err(VaultError::InvalidOperation(msg)) => Err(Box::new(CommandError::validation(msg))),
+        Err(e) => Err(Box::new(
+            CommandError::operation(ErrorCode::StorageFailed, "Failed to dismiss notification")
+                .with_details(e.to_string()),
+        )),
+    }
+}
+
+/// Get a vault's notification preferences
+#[tauri::command]
+#[specta::specta]
+#[instrument(skip_all, fields(vault_id = %input.vault_id))]
+pub async fn get_notification_preferences(
+    input: GetNotificationPreferencesRequest,
+) -> CommandResponse<NotificationPreferences> {
+    input.validate()?;
+
+    let manager = VaultManager::new();
+    manager
+        .get_notification_preferences(&input.vault_id)
+        .await
+        .map_err(|e| preferences_error(&input.vault_id, e))
+}
+
+/// Replace a vault's notification preferences
+#[tauri::command]
+#[specta::specta]
+#[instrument(skip_all, fields(vault_id = %input.vault_id))]
+pub async fn update_notification_preferences(
+    input: UpdateNotificationPreferencesRequest,
+) -> CommandResponse<NotificationPreferences> {
+    input.validate()?;
+
+    let manager = VaultManager::new();
+    manager
+        .update_notification_preferences(&input.vault_id, input.preferences)
+        .await
+        .map_err(|e| preferences_error(&input.vault_id, e))
+}
+
+fn preferences_error(vault_id: &str, error: VaultError) -> Box<CommandError> {
+    match error {
+        VaultError::NotFound(_) => Box::new(CommandError::operation(
+            ErrorCode::VaultNotFound,
+            format!("Vault '{}' not found", vault_id),
+        )),
+        e => Box::new(
+            CommandError::operation(
+                ErrorCode::StorageFailed,
+                "Failed to access notification preferences",
+            )
+            .with_details(e.to_string()),
+        ),
+    }
+}
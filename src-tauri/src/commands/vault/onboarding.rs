@@ -0,0 +1,58 @@
+//! Onboarding commands
+//!
+//! The first-run checklist, derived on the backend. Whenever a milestone
+//! becomes complete an `onboarding-milestone-completed` event is emitted, so
+//! the UI can react without polling.
+
+use crate::commands::command_types::{CommandError, CommandResponse, ErrorCode};
+use crate::services::key_management::yubikey::infrastructure::pty::app_handle::get_app_handle;
+use crate::services::vault::VaultManager;
+use crate::services::vault::domain::models::{MilestoneStatus, OnboardingStatus};
+use tauri::Emitter;
+use tracing::{debug, instrument, warn};
+
+/// Event emitted once per milestone, with a `MilestoneStatus` payload
+pub const ONBOARDING_MILESTONE_EVENT: &str = "onboarding-milestone-completed";
+
+/// Get the first-run checklist with completion times
+#[tauri::command]
+#[specta::specta]
+#[instrument]
+pub async fn get_onboarding_status() -> CommandResponse<OnboardingStatus> {
+    let (status, newly_completed) =
+        VaultManager::new()
+            .get_onboarding_status()
+            .await
+            .map_err(|e| {
+                Box::new(
+                    CommandError::operation(ErrorCode::StorageFailed, "Failed to load onboarding")
+                        .with_details(e.to_string()),
+                )
+            })?;
+
+    emit_milestones(&newly_completed);
+    Ok(status)
+}
+
+/// Re-derive onboarding after an operation that may complete a milestone
+///
+/// Never fails the calling command; problems are only logged.
+pub async fn refresh_onboarding() {
+    match VaultManager::new().get_onboarding_status().await {
+        Ok((_, newly_completed)) => emit_milestones(&newly_completed),
+        Err(e) => warn!(error = %e, "Failed to refresh onboarding milestones"),
+    }
+}
+
+fn emit_milestones(milestones: &[MilestoneStatus]) {
+    let Some(app) = get_app_handle() else {
+        return;
+    };
+
+    for milestone in milestones {
+        debug!(milestone = ?milestone.milestone, "Onboarding milestone completed");
+        if let Err(e) = app.emit(ONBOARDING_MILESTONE_EVENT, milestone) {
+            warn!(error = %e, "Failed to emit onboarding milestone");
+        }
+    }
+}
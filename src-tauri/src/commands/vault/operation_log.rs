@@ -0,0 +1,48 @@
+//! Operation log commands
+//!
+//! Checks that a vault's operation log hasn't been edited, reordered or cut
+//! short since it was written.
+
+use crate::commands::command_types::{CommandError, CommandResponse, ErrorCode, ValidateInput};
+use crate::commands::validation::{ExistingVaultId, input_rules};
+use crate::services::vault::VaultManager;
+use crate::services::vault::domain::VaultError;
+use crate::services::vault::infrastructure::persistence::OperationLogVerification;
+use serde::Deserialize;
+use tracing::instrument;
+
+/// Input for verifying a vault's operation log
+#[derive(Debug, Deserialize, specta::Type)]
+pub struct VerifyOperationLogRequest {
+    pub vault_id: String,
+}
+
+input_rules! {
+    VerifyOperationLogRequest {
+        vault_id("Vault ID"): [ExistingVaultId],
+    }
+}
+
+/// Walk a vault's operation log hash chain and report the first break
+#[tauri::command]
+#[specta::specta]
+#[instrument(skip_all, fields(vault_id = %input.vault_id))]
+pub async fn verify_operation_log(
+    input: VerifyOperationLogRequest,
+) -> CommandResponse<OperationLogVerification> {
+    input.validate()?;
+
+    let manager = VaultManager::new();
+
+    match manager.verify_operation_log(&input.vault_id) {
+        Ok(verification) => Ok(verification),
+        Err(VaultError::NotFound(_)) => Err(Box::new(CommandError::operation(
+            ErrorCode::VaultNotFound,
+            format!("Vault '{}' not found", input.vault_id),
+        ))),
+        Err(e) => Err(Box::new(
+            CommandError::operation(ErrorCode::StorageFailed, "Failed to verify operation log")
+                .with_details(e.to_string()),
+        )),
+    }
+}
@@ -0,0 +1,123 @@
+//! Vault archive retention commands
+//!
+//! Set a vault's retention policy, see which archives it would prune, and
+//! prune them. Nothing is pruned without the typed confirmation.
+
+use super::archives::archive_error;
+use crate::commands::command_types::{CommandError, CommandResponse, ValidateInput};
+use crate::commands::validation::{ExistingVaultId, input_rules};
+use crate::services::shared::infrastructure::progress::{OperationKind, begin_exclusive_operation};
+use crate::services::vault::VaultManager;
+use crate::services::vault::domain::models::{
+    ArchivePruneReport, RetentionEvaluation, RetentionPolicy,
+};
+use serde::Deserialize;
+use tracing::instrument;
+
+/// Input for setting or clearing a vault's retention policy
+#[derive(Debug, Deserialize, specta::Type)]
+pub struct SetRetentionPolicyRequest {
+    pub vault_id: String,
+    /// Missing clears the policy
+    pub policy: Option<RetentionPolicy>,
+}
+
+input_rules! {
+    SetRetentionPolicyRequest {
+        vault_id("Vault ID"): [ExistingVaultId],
+    }
+}
+
+/// Input for evaluating a vault's retention policy
+#[derive(Debug, Deserialize, specta::Type)]
+pub struct EvaluateRetentionRequest {
+    pub vault_id: String,
+}
+
+input_rules! {
+    EvaluateRetentionRequest {
+        vault_id("Vault ID"): [ExistingVaultId],
+    }
+}
+
+/// Input for pruning a vault's archives
+#[derive(Debug, Deserialize, specta::Type)]
+pub struct PruneArchivesRequest {
+    pub vault_id: String,
+    /// Archives to prune; leave empty with `policy`
+    #[serde(default)]
+    pub archive_ids: Vec<String>,
+    /// Prune exactly the archives the retention policy marks eligible
+    #[serde(default)]
+    pub policy: bool,
+    /// The user must type "PRUNE"
+    pub confirmation: Option<String>,
+}
+
+input_rules! {
+    PruneArchivesRequest {
+        vault_id("Vault ID"): [ExistingVaultId],
+    }
+}
+
+/// Set or clear a vault's retention policy
+#[tauri::command]
+#[specta::specta]
+#[instrument(skip_all, fields(vault_id = %input.vault_id))]
+pub async fn set_retention_policy(
+    input: SetRetentionPolicyRequest,
+) -> CommandResponse<Option<RetentionPolicy>> {
+    input.validate()?;
+
+    let manager = VaultManager::new();
+    manager
+        .set_retention_policy(&input.vault_id, input.policy)
+        .await
+        .map_err(|e| archive_error(&input.vault_id, e))
+}
+
+/// Classify a vault's archives as retained (and by which rule) or eligible
+/// for pruning; nothing is deleted
+#[tauri::command]
+#[specta::specta]
+#[instrument(skip_all, fields(vault_id = %input.vault_id))]
+pub async fn evaluate_retention(
+    input: EvaluateRetentionRequest,
+) -> CommandResponse<RetentionEvaluation> {
+    input.validate()?;
+
+    let manager = VaultManager::new();
+    manager
+        .evaluate_retention(&input.vault_id)
+        .await
+        .map_err(|e| archive_error(&input.vault_id, e))
+}
+
+/// Remove archives from the index, deleting archive files nothing refers to
+///
+/// The vault's latest archive and immutable archives are never pruned.
+#[tauri::command]
+#[specta::specta]
+#[instrument(skip_all, fields(
+    vault_id = %input.vault_id,
+    archive_count = input.archive_ids.len(),
+    policy = input.policy
+))]
+pub async fn prune_archives(input: PruneArchivesRequest) -> CommandResponse<ArchivePruneReport> {
+    input.validate()?;
+
+    // Nothing else may write archives while they are deleted
+    let _operation = begin_exclusive_operation(OperationKind::Maintenance)
+        .map_err(|e| Box::new(CommandError::from(e)))?;
+
+    let manager = VaultManager::new();
+    manager
+        .prune_archives(
+            &input.vault_id,
+            &input.archive_ids,
+            input.policy,
+            input.confirmation.as_deref(),
+        )
+        .await
+        .map_err(|e| archive_error(&input.vault_id, e))
+}
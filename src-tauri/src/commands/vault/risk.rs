@@ -0,0 +1,48 @@
+//! Vault risk commands
+//!
+//! Flags vaults whose keys all live in one place, so losing a laptop or a
+//! single USB stick wouldn't lock the user out for good.
+
+use crate::commands::command_types::{CommandError, CommandResponse, ErrorCode, ValidateInput};
+use crate::commands::validation::{ExistingVaultId, input_rules};
+use crate::services::vault::VaultManager;
+use crate::services::vault::domain::VaultError;
+use crate::services::vault::domain::models::VaultRiskAssessment;
+use serde::Deserialize;
+use tracing::instrument;
+
+/// Input for assessing a vault's key storage risks
+#[derive(Debug, Deserialize, specta::Type)]
+pub struct AssessVaultRiskRequest {
+    pub vault_id: String,
+}
+
+input_rules! {
+    AssessVaultRiskRequest {
+        vault_id("Vault ID"): [ExistingVaultId],
+    }
+}
+
+/// Classify where each of a vault's keys lives and flag storage risks
+#[tauri::command]
+#[specta::specta]
+#[instrument(skip_all, fields(vault_id = %input.vault_id))]
+pub async fn assess_vault_risk(
+    input: AssessVaultRiskRequest,
+) -> CommandResponse<VaultRiskAssessment> {
+    input.validate()?;
+
+    let manager = VaultManager::new();
+
+    match manager.assess_vault_risk(&input.vault_id).await {
+        Ok(assessment) => Ok(assessment),
+        Err(VaultError::NotFound(_)) => Err(Box::new(CommandError::operation(
+            ErrorCode::VaultNotFound,
+            format!("Vault '{}' not found", input.vault_id),
+        ))),
+        Err(e) => Err(Box::new(
+            CommandError::operation(ErrorCode::StorageFailed, "Failed to assess vault risk")
+                .with_details(e.to_string()),
+        )),
+    }
+}
@@ -0,0 +1,79 @@
+//! Shared vault store commands
+//!
+//! Let OS accounts on one machine keep their vaults in one store, see who is
+//! changing it and clear a lock left behind by an account that's gone.
+
+use crate::commands::command_types::{CommandError, CommandResponse, ErrorCode};
+use crate::services::vault::VaultManager;
+use crate::services::vault::domain::VaultError;
+use crate::services::vault::domain::models::SharedStoreStatus;
+use serde::Deserialize;
+use std::path::Path;
+use tracing::instrument;
+
+/// Input for choosing the shared store
+#[derive(Debug, Deserialize, specta::Type)]
+pub struct SetSharedStoreRequest {
+    /// Folder every sharing account can write to, or `None` to stop sharing
+    pub store_dir: Option<String>,
+}
+
+/// This profile's shared vault store and who is changing it
+#[tauri::command]
+#[specta::specta]
+#[instrument]
+pub async fn get_shared_store_status() -> CommandResponse<SharedStoreStatus> {
+    VaultManager::new()
+        .get_shared_store_status()
+        .map_err(shared_store_error)
+}
+
+/// Keep new vaults in a store shared with other accounts, or stop sharing
+///
+/// The folder must already exist outside this account's app data, writable
+/// by every account that shares it. Vaults this account already has stay in
+/// its profile.
+#[tauri::command]
+#[specta::specta]
+#[instrument(skip_all, fields(shared = input.store_dir.is_some()))]
+pub async fn set_shared_store(input: SetSharedStoreRequest) -> CommandResponse<SharedStoreStatus> {
+    VaultManager::new()
+        .set_shared_store(input.store_dir.as_deref().map(Path::new))
+        .map_err(shared_store_error)
+}
+
+/// Take over the shared store from an account that left it locked
+///
+/// Only a holder that hasn't been seen lately can be taken over.
+#[tauri::command]
+#[specta::specta]
+#[instrument]
+pub async fn take_over_shared_store() -> CommandResponse<SharedStoreStatus> {
+    VaultManager::new()
+        .take_over_shared_store()
+        .map_err(shared_store_error)
+}
+
+fn shared_store_error(error: VaultError) -> Box<CommandError> {
+    match error {
+        e @ VaultError::StoreInUseBy { stale: false, .. } => Box::new(
+            CommandError::operation(ErrorCode::StoreInUse, e.to_string())
+                .with_recovery_guidance("Try again when the other account is done"),
+        ),
+        e @ VaultError::StoreInUseBy { .. } => Box::new(CommandError::operation(
+            ErrorCode::StoreInUse,
+            e.to_string(),
+        )),
+        VaultError::InvalidOperation(msg) => Box::new(
+            CommandError::operation(ErrorCode::InvalidInput, msg)
+                .with_recovery_guidance("Choose a folder outside this account's app data"),
+        ),
+        e => Box::new(
+            CommandError::operation(
+                ErrorCode::StorageFailed,
+                "Failed to access the shared store",
+            )
+            .with_details(e.to_string()),
+        ),
+    }
+}
@@ -0,0 +1,41 @@
+//! Shutdown safety commands
+//!
+//! The UI calls `get_shutdown_status` (or receives the `shutdown-blocked`
+//! event when a quit was held back) to show what's still running, and
+//! `force_quit_and_abort` once the user chooses to quit anyway.
+
+use crate::commands::types::{CommandError, CommandResponse, ErrorCode};
+use crate::logging::spawn_blocking;
+use crate::prelude::*;
+use crate::services::key_management::yubikey::infrastructure::pty::app_handle::get_app_handle;
+use crate::services::vault::VaultManager;
+use crate::services::vault::domain::models::{ForcedQuitReport, ShutdownCheck};
+
+/// What quitting now would interrupt
+#[tauri::command]
+#[specta::specta]
+#[instrument]
+pub async fn get_shutdown_status() -> CommandResponse<ShutdownCheck> {
+    Ok(VaultManager::new().get_shutdown_status())
+}
+
+/// Cancel running operations and quit once they've cleaned up
+///
+/// Waits a bounded time for cancelled operations, records each affected
+/// vault's interruption for the next start, then exits the app.
+#[tauri::command]
+#[specta::specta]
+#[instrument]
+pub async fn force_quit_and_abort() -> CommandResponse<ForcedQuitReport> {
+    let report = spawn_blocking(|| VaultManager::new().force_quit_and_abort())
+        .await
+        .map_err(|e| {
+            CommandError::operation(ErrorCode::InternalError, format!("Task error: {e}"))
+        })?;
+
+    match get_app_handle() {
+        Some(app) => app.exit(0),
+        None => warn!("No app handle; forced quit recorded but the app keeps running"),
+    }
+    Ok(report)
+}
@@ -2,10 +2,14 @@
 //!
 //! Provides commands for retrieving vault statistics for the R2 UI.
 
+use crate::commands::command_types::ValidateInput;
+use crate::commands::validation::{ExistingVaultId, input_rules};
 use crate::prelude::*;
+use crate::services::shared::infrastructure::registry_revision;
 use crate::services::vault::application::services::{
     GlobalVaultStatistics, VaultStatistics, VaultStatisticsService,
 };
+use crate::types::FormatHints;
 use serde::{Deserialize, Serialize};
 
 /// Request for getting single vault statistics
@@ -15,6 +19,12 @@ pub struct GetVaultStatisticsRequest {
     pub vault_id: String,
 }
 
+input_rules! {
+    GetVaultStatisticsRequest {
+        vault_id("Vault ID"): [ExistingVaultId],
+    }
+}
+
 /// Response containing vault statistics
 #[derive(Debug, Clone, Serialize, Deserialize, specta::Type)]
 pub struct GetVaultStatisticsResponse {
@@ -36,6 +46,8 @@ pub struct GetAllVaultStatisticsResponse {
     pub success: bool,
     pub statistics: Option<GlobalVaultStatistics>,
     pub error: Option<String>,
+    /// Change revision the statistics reflect (see `registry-changed` events)
+    pub registry_revision: u64,
 }
 
 /// Get statistics for a specific vault
@@ -44,17 +56,18 @@ pub struct GetAllVaultStatisticsResponse {
 /// It provides real-time data about vault usage, key status, and encryption history.
 #[tauri::command]
 #[specta::specta]
+#[instrument(skip_all)]
 pub async fn get_vault_statistics(
     request: GetVaultStatisticsRequest,
 ) -> Result<GetVaultStatisticsResponse, String> {
     debug!(vault_id = %request.vault_id, "Getting vault statistics");
 
     // Validate vault ID
-    if request.vault_id.is_empty() {
+    if let Err(e) = request.validate() {
         return Ok(GetVaultStatisticsResponse {
             success: false,
             statistics: None,
-            error: Some("Vault ID cannot be empty".to_string()),
+            error: Some(e.message),
         });
     }
 
@@ -116,6 +129,7 @@ pub async fn get_vault_statistics(
 /// It provides a comprehensive overview of vault usage and key management.
 #[tauri::command]
 #[specta::specta]
+#[instrument(skip_all)]
 pub async fn get_all_vault_statistics(
     request: GetAllVaultStatisticsRequest,
 ) -> Result<GetAllVaultStatisticsResponse, String> {
@@ -123,6 +137,7 @@ pub async fn get_all_vault_statistics(
 
     // Create service and get statistics
     let service = VaultStatisticsService::new();
+    let registry_revision = registry_revision();
 
     match service.get_all_vault_statistics() {
         Ok(mut statistics) => {
@@ -173,6 +188,7 @@ pub async fn get_all_vault_statistics(
                         .iter()
                         .map(|v| v.total_size_bytes)
                         .sum();
+                    statistics.format_hints = Some(FormatHints::size(statistics.total_size_bytes));
                 }
             }
 
@@ -187,6 +203,7 @@ pub async fn get_all_vault_statistics(
                 success: true,
                 statistics: Some(statistics),
                 error: None,
+                registry_revision,
             })
         }
         Err(e) => {
@@ -196,6 +213,7 @@ pub async fn get_all_vault_statistics(
                 success: false,
                 statistics: None,
                 error: Some(format!("Failed to get vault statistics: {}", e)),
+                registry_revision,
             })
         }
     }
@@ -212,6 +230,8 @@ fn test_request_validation() {
             vault_id: "".to_string(),
         };
         assert!(request.vault_id.is_empty());
+        let error = request.validate().unwrap_err();
+        assert_eq!(error.validation.unwrap().field, "vault_id");
 
         // Test valid vault ID
         let request = GetVaultStatisticsRequest {
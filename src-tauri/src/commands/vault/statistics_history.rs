@@ -0,0 +1,64 @@
+//! Vault statistics history commands
+//!
+//! Serve a vault's recorded statistics as a series for trend charts.
+
+use crate::commands::command_types::{CommandError, CommandResponse, ErrorCode, ValidateInput};
+use crate::commands::validation::{ExistingVaultId, input_rules};
+use crate::services::vault::VaultManager;
+use crate::services::vault::domain::VaultError;
+use crate::services::vault::domain::models::{StatisticsRange, VaultStatisticsHistory};
+use serde::Deserialize;
+use tracing::instrument;
+
+/// Input for reading a vault's statistics history
+#[derive(Debug, Deserialize, specta::Type)]
+pub struct GetVaultStatisticsHistoryRequest {
+    pub vault_id: String,
+    /// Defaults to the last month
+    #[serde(default)]
+    pub range: StatisticsRange,
+    /// Most points to return (default 60, at most 365)
+    pub max_points: Option<u32>,
+}
+
+input_rules! {
+    GetVaultStatisticsHistoryRequest {
+        vault_id("Vault ID"): [ExistingVaultId],
+    }
+}
+
+/// A vault's statistics over a range, downsampled for charting
+#[tauri::command]
+#[specta::specta]
+#[instrument(skip_all, fields(vault_id = %input.vault_id))]
+pub async fn get_vault_statistics_history(
+    input: GetVaultStatisticsHistoryRequest,
+) -> CommandResponse<VaultStatisticsHistory> {
+    input.validate()?;
+
+    let manager = VaultManager::new();
+    manager
+        .get_statistics_history(
+            &input.vault_id,
+            input.range,
+            input.max_points.map(|points| points as usize),
+        )
+        .await
+        .map_err(|e| statistics_history_error(&input.vault_id, e))
+}
+
+fn statistics_history_error(vault_id: &str, error: VaultError) -> Box<CommandError> {
+    match error {
+        VaultError::NotFound(_) => Box::new(CommandError::operation(
+            ErrorCode::VaultNotFound,
+            format!("Vault '{}' not found", vault_id),
+        )),
+        e => Box::new(
+            CommandError::operation(
+                ErrorCode::StorageFailed,
+                "Failed to read the statistics history",
+            )
+            .with_details(e.to_string()),
+        ),
+    }
+}
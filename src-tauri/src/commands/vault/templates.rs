@@ -0,0 +1,119 @@
+//! Vault template commands
+//!
+//! Commands for browsing built-in vault templates and checking a vault's
+//! protection status against the template it was created from, including
+//! whether reserved YubiKey slots hold that status back.
+
+use crate::commands::command_types::{CommandError, CommandResponse, ErrorCode, ValidateInput};
+use crate::commands::validation::{ExistingVaultId, input_rules};
+use crate::services::vault::VaultManager;
+use crate::services::vault::application::services::ProtectionStatus;
+use crate::services::vault::domain::VaultError;
+use crate::services::vault::domain::models::VaultTemplate;
+use serde::{Deserialize, Serialize};
+use tracing::instrument;
+
+/// Response containing the template catalog
+#[derive(Debug, Serialize, specta::Type)]
+pub struct ListVaultTemplatesResponse {
+    pub templates: Vec<VaultTemplate>,
+}
+
+/// Input for getting a vault's protection status
+#[derive(Debug, Deserialize, specta::Type)]
+pub struct GetProtectionStatusRequest {
+    pub vault_id: String,
+}
+
+input_rules! {
+    GetProtectionStatusRequest {
+        vault_id("Vault ID"): [ExistingVaultId],
+    }
+}
+
+/// Input for choosing whether reserved YubiKey slots hold back a vault's policy
+#[derive(Debug, Deserialize, specta::Type)]
+pub struct SetAllowPendingYubiKeysRequest {
+    pub vault_id: String,
+    /// True to let reserved slots stand without failing the policy
+    pub allowed: bool,
+}
+
+input_rules! {
+    SetAllowPendingYubiKeysRequest {
+        vault_id("Vault ID"): [ExistingVaultId],
+    }
+}
+
+/// List built-in vault templates
+#[tauri::command]
+#[specta::specta]
+#[instrument]
+pub async fn list_vault_templates() -> CommandResponse<ListVaultTemplatesResponse> {
+    let manager = VaultManager::new();
+
+    match manager.list_vault_templates() {
+        Ok(templates) => Ok(ListVaultTemplatesResponse { templates }),
+        Err(e) => Err(Box::new(
+            CommandError::operation(
+                ErrorCode::ConfigurationError,
+                "Failed to load vault templates",
+            )
+            .with_details(e.to_string()),
+        )),
+    }
+}
+
+/// Get protection status (policy, freshness, checklist completion) for a vault
+#[tauri::command]
+#[specta::specta]
+#[instrument(skip_all, fields(vault_id = %input.vault_id))]
+pub async fn get_protection_status(
+    input: GetProtectionStatusRequest,
+) -> CommandResponse<ProtectionStatus> {
+    input.validate()?;
+
+    let manager = VaultManager::new();
+
+    match manager.get_protection_status(&input.vault_id).await {
+        Ok(status) => Ok(status),
+        Err(VaultError::NotFound(_)) => Err(Box::new(CommandError::operation(
+            ErrorCode::VaultNotFound,
+            format!("Vault '{}' not found", input.vault_id),
+        ))),
+        Err(e) => Err(Box::new(
+            CommandError::operation(ErrorCode::StorageFailed, "Failed to get protection status")
+                .with_details(e.to_string()),
+        )),
+    }
+}
+
+/// Choose whether reserved YubiKey slots hold back a vault's protection policy
+#[tauri::command]
+#[specta::specta]
+#[instrument(skip_all, fields(vault_id = %input.vault_id, allowed = input.allowed))]
+pub async fn set_allow_pending_yubikeys(
+    input: SetAllowPendingYubiKeysRequest,
+) -> CommandResponse<ProtectionStatus> {
+    input.validate()?;
+
+    let manager = VaultManager::new();
+
+    match manager
+        .set_allow_pending_yubikeys(&input.vault_id, input.allowed)
+        .await
+    {
+        Ok(status) => Ok(status),
+        Err(VaultError::NotFound(_)) => Err(Box::new(CommandError::operation(
+            ErrorCode::VaultNotFound,
+            format!("Vault '{}' not found", input.vault_id),
+        ))),
+        Err(e) => Err(Box::new(
+            CommandError::operation(
+                ErrorCode::StorageFailed,
+                "Failed to update the reserved YubiKey policy",
+            )
+            .with_details(e.to_string()),
+        )),
+    }
+}
@@ -0,0 +1,90 @@
+//! Vault transform commands
+//!
+//! Commands for configuring the lossless transforms (TIFF→PNG, JSON
+//! minification, gzip re-encoding) a vault applies to copies of its files
+//! before they're encrypted.
+
+use crate::commands::command_types::{CommandError, CommandResponse, ErrorCode, ValidateInput};
+use crate::commands::validation::{ExistingVaultId, input_rules};
+use crate::services::file::infrastructure::file_operations::TransformSettings;
+use crate::services::vault::VaultManager;
+use crate::services::vault::domain::VaultError;
+use serde::Deserialize;
+use tracing::instrument;
+
+/// Input for reading a vault's transform rules
+#[derive(Debug, Deserialize, specta::Type)]
+pub struct GetVaultTransformsRequest {
+    pub vault_id: String,
+}
+
+input_rules! {
+    GetVaultTransformsRequest {
+        vault_id("Vault ID"): [ExistingVaultId],
+    }
+}
+
+/// Input for replacing a vault's transform rules
+#[derive(Debug, Deserialize, specta::Type)]
+pub struct UpdateVaultTransformsRequest {
+    pub vault_id: String,
+    pub transforms: TransformSettings,
+}
+
+input_rules! {
+    UpdateVaultTransformsRequest {
+        vault_id("Vault ID"): [ExistingVaultId],
+    }
+}
+
+/// Get a vault's pre-encryption transform rules
+#[tauri::command]
+#[specta::specta]
+#[instrument(skip_all, fields(vault_id = %input.vault_id))]
+pub async fn get_vault_transforms(
+    input: GetVaultTransformsRequest,
+) -> CommandResponse<TransformSettings> {
+    input.validate()?;
+
+    let manager = VaultManager::new();
+    manager
+        .get_vault_transforms(&input.vault_id)
+        .await
+        .map_err(|e| transforms_error(&input.vault_id, e))
+}
+
+/// Replace a vault's pre-encryption transform rules
+///
+/// Every rule must match on a content type, a glob, or both. Transforms
+/// apply to archives written after the change.
+#[tauri::command]
+#[specta::specta]
+#[instrument(skip_all, fields(vault_id = %input.vault_id))]
+pub async fn update_vault_transforms(
+    input: UpdateVaultTransformsRequest,
+) -> CommandResponse<TransformSettings> {
+    input.validate()?;
+
+    let manager = VaultManager::new();
+    manager
+        .update_vault_transforms(&input.vault_id, input.transforms)
+        .await
+        .map_err(|e| transforms_error(&input.vault_id, e))
+}
+
+fn transforms_error(vault_id: &str, error: VaultError) -> Box<CommandError> {
+    match error {
+        VaultError::NotFound(_) => Box::new(CommandError::operation(
+            ErrorCode::VaultNotFound,
+            format!("Vault '{}' not found", vault_id),
+        )),
+        VaultError::InvalidOperation(msg) => Box::new(CommandError::validation(msg)),
+        e => Box::new(
+            CommandError::operation(
+                ErrorCode::StorageFailed,
+                "Failed to access vault transforms",
+            )
+            .with_details(e.to_string()),
+        ),
+    }
+}
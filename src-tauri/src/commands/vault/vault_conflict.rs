@@ -0,0 +1,60 @@
+//! Vault conflict commands
+//!
+//! Settle a vault changed on two devices at once, after a sync service kept
+//! one device's files over the other's or left conflicting copies beside
+//! them. `list_vaults` flags such vaults as `conflict_suspected`.
+
+use crate::commands::command_types::{CommandError, CommandResponse, ErrorCode, ValidateInput};
+use crate::commands::validation::{ExistingVaultId, input_rules};
+use crate::services::vault::VaultManager;
+use crate::services::vault::domain::VaultError;
+use crate::services::vault::domain::models::{ConflictResolution, ConflictStrategy};
+use serde::Deserialize;
+use tracing::instrument;
+
+/// Input for resolving a vault conflict
+#[derive(Debug, Deserialize, specta::Type)]
+pub struct ResolveVaultConflictRequest {
+    pub vault_id: String,
+    pub strategy: ConflictStrategy,
+}
+
+input_rules! {
+    ResolveVaultConflictRequest {
+        vault_id("Vault ID"): [ExistingVaultId],
+    }
+}
+
+/// Keep one device's copy of a vault, or merge both devices' archives
+///
+/// Unblocks changes to the vault and removes the conflicting copies.
+#[tauri::command]
+#[specta::specta]
+#[instrument(skip_all, fields(vault_id = %input.vault_id, strategy = ?input.strategy))]
+pub async fn resolve_vault_conflict(
+    input: ResolveVaultConflictRequest,
+) -> CommandResponse<ConflictResolution> {
+    input.validate()?;
+
+    let manager = VaultManager::new();
+
+    match manager.resolve_vault_conflict(&input.vault_id, input.strategy) {
+        Ok(resolution) => Ok(resolution),
+        Err(VaultError::NotFound(_)) => Err(Box::new(CommandError::operation(
+            ErrorCode::VaultNotFound,
+            format!("Vault '{}' not found", input.vault_id),
+        ))),
+        Err(VaultError::InvalidOperation(msg)) => Err(Box::new(
+            CommandError::operation(ErrorCode::InvalidInput, msg)
+                .with_recovery_guidance("Refresh the vault list and choose another option"),
+        )),
+        Err(e @ VaultError::ConflictSuspected { .. }) => Err(Box::new(CommandError::operation(
+            ErrorCode::VaultConflictSuspected,
+            e.to_string(),
+        ))),
+        Err(e) => Err(Box::new(
+            CommandError::operation(ErrorCode::StorageFailed, "Failed to resolve vault conflict")
+                .with_details(e.to_string()),
+        )),
+    }
+}
@@ -2,10 +2,18 @@
 //!
 //! Commands for creating, listing, and managing vaults.
 
-use crate::commands::command_types::{CommandError, CommandResponse, ErrorCode};
+use crate::commands::command_types::{CommandError, CommandResponse, ErrorCode, ValidateInput};
+use crate::commands::listing::{Listable, PageRequest, SortSpec, compare_text, sort_and_paginate};
+use crate::commands::types::ValidationRule;
+use crate::commands::validation::{ExistingVaultId, NonEmptyList, input_rules, invalid};
+use crate::logging::current_trace_id;
+use crate::services::shared::infrastructure::{WindowSessions, registry_revision};
 use crate::services::vault::VaultManager;
+use crate::services::vault::application::services::compare_user_order;
+use crate::services::vault::domain::VaultError;
 use crate::services::vault::domain::models::VaultSummary;
 use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
 use tracing::instrument;
 
 /// Input for creating a new vault
@@ -13,6 +21,8 @@
 pub struct CreateVaultRequest {
     pub name: String,
     pub description: Option<String>,
+    /// Optional built-in template to apply (see `list_vault_templates`)
+    pub template_id: Option<String>,
 }
 
 /// Response from vault creation
@@ -21,10 +31,43 @@ pub struct CreateVaultResponse {
     pub vault: VaultSummary,
 }
 
+/// Fields `list_vaults` can sort by
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+#[serde(rename_all = "snake_case")]
+pub enum VaultSortField {
+    /// Favorites first, then the user's order, then name
+    UserOrder,
+    Name,
+    CreatedAt,
+    KeyCount,
+}
+
+impl Listable for VaultSummary {
+    type SortField = VaultSortField;
+
+    fn list_id(&self) -> &str {
+        &self.id
+    }
+
+    fn compare_by(&self, other: &Self, field: VaultSortField) -> Ordering {
+        match field {
+            VaultSortField::UserOrder => compare_user_order(self, other),
+            VaultSortField::Name => compare_text(&self.name, &other.name),
+            VaultSortField::CreatedAt => self.created_at.cmp(&other.created_at),
+            VaultSortField::KeyCount => self.key_count.cmp(&other.key_count),
+        }
+    }
+}
+
 /// Response containing list of vaults
 #[derive(Debug, Serialize, specta::Type)]
 pub struct ListVaultsResponse {
+    /// The requested page, in the user's order unless asked otherwise
     pub vaults: Vec<VaultSummary>,
+    /// Vaults in all pages
+    pub total_count: usize,
+    /// Change revision the list reflects (see `registry-changed` events)
+    pub registry_revision: u64,
 }
 
 /// Response containing current vault
@@ -39,6 +82,12 @@ pub struct SetCurrentVaultRequest {
     pub vault_id: String,
 }
 
+input_rules! {
+    SetCurrentVaultRequest {
+        vault_id("Vault ID"): [ExistingVaultId],
+    }
+}
+
 /// Response from setting current vault
 #[derive(Debug, Serialize, specta::Type)]
 pub struct SetCurrentVaultResponse {
@@ -53,6 +102,38 @@ pub struct DeleteVaultRequest {
     pub force: bool, // If true, delete even if vault has keys
 }
 
+input_rules! {
+    DeleteVaultRequest {
+        vault_id("Vault ID"): [ExistingVaultId],
+    }
+}
+
+/// Input for pinning a vault to the top of the vault list
+#[derive(Debug, Deserialize, specta::Type)]
+pub struct SetVaultFavoriteRequest {
+    pub vault_id: String,
+    pub favorite: bool,
+}
+
+input_rules! {
+    SetVaultFavoriteRequest {
+        vault_id("Vault ID"): [ExistingVaultId],
+    }
+}
+
+/// Input for reordering the vault list
+#[derive(Debug, Deserialize, specta::Type)]
+pub struct ReorderVaultsRequest {
+    /// Every vault's ID, in the new order
+    pub ordered_ids: Vec<String>,
+}
+
+input_rules! {
+    ReorderVaultsRequest {
+        ordered_ids("Vault order"): [NonEmptyList],
+    }
+}
+
 /// Response from vault deletion
 #[derive(Debug, Serialize, specta::Type)]
 pub struct DeleteVaultResponse {
@@ -67,122 +148,242 @@ pub struct DeleteVaultResponse {
 pub async fn create_vault(input: CreateVaultRequest) -> CommandResponse<CreateVaultResponse> {
     let manager = VaultManager::new();
 
-    match manager.create_vault(input.name, input.description).await {
-        Ok(vault_summary) => Ok(CreateVaultResponse {
-            vault: vault_summary,
-        }),
+    match manager
+        .create_vault(input.name, input.description, input.template_id)
+        .await
+    {
+        Ok(vault_summary) => {
+            super::refresh_onboarding().await;
+            Ok(CreateVaultResponse {
+                vault: vault_summary,
+            })
+        }
         Err(e) => Err(Box::new(CommandError {
             code: match e {
                 crate::services::vault::domain::VaultError::InvalidName(_) => {
                     ErrorCode::InvalidInput
                 }
+                crate::services::vault::domain::VaultError::NameConfusable { .. } => {
+                    ErrorCode::InvalidInput
+                }
                 crate::services::vault::domain::VaultError::AlreadyExists(_) => {
                     ErrorCode::VaultAlreadyExists
                 }
+                crate::services::vault::domain::VaultError::TemplateNotFound(_) => {
+                    ErrorCode::InvalidInput
+                }
+                crate::services::vault::domain::VaultError::StoreInUseBy { .. } => {
+                    ErrorCode::StoreInUse
+                }
                 _ => ErrorCode::StorageFailed,
             },
             message: e.to_string(),
-            details: None,
+            details: match &e {
+                crate::services::vault::domain::VaultError::NameConfusable {
+                    conflicts_with,
+                    ..
+                } => Some(conflicts_with.clone()),
+                _ => None,
+            },
             recovery_guidance: Some(match e {
                 crate::services::vault::domain::VaultError::InvalidName(_) => {
                     "Enter a valid vault name".to_string()
                 }
+                crate::services::vault::domain::VaultError::NameConfusable { .. } => {
+                    "Choose a name that is clearly different from your other vaults".to_string()
+                }
                 crate::services::vault::domain::VaultError::AlreadyExists(_) => {
                     "Choose a different vault name".to_string()
                 }
+                crate::services::vault::domain::VaultError::TemplateNotFound(_) => {
+                    "Choose a template from the template list".to_string()
+                }
+                crate::services::vault::domain::VaultError::StoreInUseBy { .. } => {
+                    "Try again when the other account is done with the shared vaults".to_string()
+                }
                 _ => "Check disk space and permissions".to_string(),
             }),
             user_actionable: true,
-            trace_id: None,
+            trace_id: current_trace_id(),
             span_id: None,
+            validation: None,
+            help_available: true,
         })),
     }
 }
 
-/// List all vaults
+/// List vaults, one page at a time
 #[tauri::command]
 #[specta::specta]
 #[instrument]
-pub async fn list_vaults() -> CommandResponse<ListVaultsResponse> {
+pub async fn list_vaults(
+    sort: Option<SortSpec<VaultSortField>>,
+    page: Option<PageRequest>,
+) -> CommandResponse<ListVaultsResponse> {
     let manager = VaultManager::new();
+    // Read before listing so a change made meanwhile shows up as newer
+    let registry_revision = registry_revision();
 
     match manager.list_vaults().await {
-        Ok(vaults) => Ok(ListVaultsResponse { vaults }),
+        Ok(vaults) => {
+            let page = sort_and_paginate(vaults, sort, VaultSortField::UserOrder, page);
+            Ok(ListVaultsResponse {
+                vaults: page.items,
+                total_count: page.total_count,
+                registry_revision,
+            })
+        }
         Err(e) => Err(Box::new(CommandError {
             code: ErrorCode::StorageFailed,
             message: "Failed to list vaults".to_string(),
             details: Some(e.to_string()),
             recovery_guidance: Some("Check application data directory".to_string()),
             user_actionable: false,
-            trace_id: None,
+            trace_id: current_trace_id(),
             span_id: None,
+            validation: None,
+            help_available: true,
         })),
     }
 }
 
-/// Get the current active vault (deprecated - UI should track this)
+/// Pin a vault to the top of the vault list, or unpin it
+#[tauri::command]
+#[specta::specta]
+#[instrument(skip_all, fields(vault_id = %input.vault_id, favorite = input.favorite))]
+pub async fn set_vault_favorite(input: SetVaultFavoriteRequest) -> CommandResponse<()> {
+    input.validate()?;
+
+    VaultManager::new()
+        .set_vault_favorite(&input.vault_id, input.favorite)
+        .await
+        .map_err(order_error)
+}
+
+/// Set the order of the vault list
+///
+/// `ordered_ids` must list every vault exactly once. Vaults created
+/// afterwards are added at the end, and favorites still come first.
+#[tauri::command]
+#[specta::specta]
+#[instrument(skip_all, fields(vault_count = input.ordered_ids.len()))]
+pub async fn reorder_vaults(input: ReorderVaultsRequest) -> CommandResponse<()> {
+    input.validate()?;
+
+    VaultManager::new()
+        .reorder_vaults(&input.ordered_ids)
+        .await
+        .map_err(order_error)
+}
+
+fn order_error(e: VaultError) -> Box<CommandError> {
+    match e {
+        VaultError::InvalidOperation(msg) => Box::new(
+            invalid("ordered_ids", ValidationRule::Format, msg)
+                .with_recovery_guidance("Refresh the vault list and try again"),
+        ),
+        VaultError::NotFound(vault_id) => Box::new(CommandError::operation(
+            ErrorCode::VaultNotFound,
+            format!("Vault '{}' not found", vault_id),
+        )),
+        e => Box::new(
+            CommandError::operation(ErrorCode::StorageFailed, "Failed to save vault order")
+                .with_details(e.to_string())
+                .with_recovery_guidance("Check that the config directory is writable"),
+        ),
+    }
+}
+
+/// Get the vault selected in a window
+///
+/// Each window keeps its own current vault. `window_label` reads another
+/// window's selection; it defaults to the calling window.
+#[tauri::command]
+#[specta::specta]
+#[instrument(skip(window))]
+pub async fn get_current_vault(
+    window: tauri::Window,
+    window_label: Option<String>,
+) -> CommandResponse<GetCurrentVaultResponse> {
+    let label = window_label.unwrap_or_else(|| window.label().to_string());
+    let vault_id = WindowSessions::global().current_vault(&label);
+    Ok(GetCurrentVaultResponse {
+        vault: load_summary(vault_id).await,
+    })
+}
+
+/// Get the default vault for contexts without a window, such as deep links
+///
+/// This is the vault most recently selected in any open window.
 #[tauri::command]
 #[specta::specta]
 #[instrument]
-pub async fn get_current_vault() -> CommandResponse<GetCurrentVaultResponse> {
-    // This endpoint is deprecated - UI should track the current vault
-    // Return None for now to maintain API compatibility
-    Ok(GetCurrentVaultResponse { vault: None })
+pub async fn get_default_vault() -> CommandResponse<GetCurrentVaultResponse> {
+    let vault_id = WindowSessions::global().default_vault();
+    Ok(GetCurrentVaultResponse {
+        vault: load_summary(vault_id).await,
+    })
 }
 
-/// Set the current active vault (deprecated - UI should track this)
+/// Set the current vault of the calling window
+///
+/// Other windows keep their own selection.
 #[tauri::command]
 #[specta::specta]
-#[instrument(skip_all, fields(vault_id = %input.vault_id))]
+#[instrument(skip_all, fields(vault_id = %input.vault_id, window = %window.label()))]
 pub async fn set_current_vault(
+    window: tauri::Window,
     input: SetCurrentVaultRequest,
 ) -> CommandResponse<SetCurrentVaultResponse> {
-    // This endpoint is deprecated - UI should track the current vault
-    // Just verify the vault exists and return success
+    input.validate()?;
+
     let manager = VaultManager::new();
-    let vault = match manager.get_vault(&input.vault_id).await {
-        Ok(v) => v,
-        Err(_) => {
-            return Err(Box::new(CommandError {
-                code: ErrorCode::VaultNotFound,
-                message: format!("Vault '{}' not found", input.vault_id),
-                details: None,
-                recovery_guidance: Some("Check vault ID and try again".to_string()),
-                user_actionable: true,
-                trace_id: None,
-                span_id: None,
-            }));
-        }
-    };
+    let vault = manager
+        .get_vault(&input.vault_id)
+        .await
+        .map_err(load_vault_error)?;
+
+    WindowSessions::global().set_current_vault(window.label(), &input.vault_id);
 
-    // Just return success with the vault summary
     Ok(SetCurrentVaultResponse {
         success: true,
         vault: vault.to_summary(),
     })
 }
 
+/// The vault was checked to exist, so failing to load it is a storage error
+fn load_vault_error(e: VaultError) -> Box<CommandError> {
+    Box::new(
+        CommandError::operation(ErrorCode::StorageFailed, "Failed to load vault")
+            .with_details(e.to_string()),
+    )
+}
+
+/// Summary of a session's vault, dropping pointers to vaults that are gone
+async fn load_summary(vault_id: Option<String>) -> Option<VaultSummary> {
+    let vault_id = vault_id?;
+    match VaultManager::new().get_vault(&vault_id).await {
+        Ok(vault) => Some(vault.to_summary()),
+        Err(_) => {
+            WindowSessions::global().forget_vault(&vault_id);
+            None
+        }
+    }
+}
+
 /// Delete a vault
 #[tauri::command]
 #[specta::specta]
 #[instrument(skip_all, fields(vault_id = %input.vault_id, force = %input.force))]
 pub async fn delete_vault(input: DeleteVaultRequest) -> CommandResponse<DeleteVaultResponse> {
-    // Load the vault to check if it exists and has keys
+    input.validate()?;
+
+    // Load the vault to check whether it has keys
     let manager = VaultManager::new();
-    let vault = match manager.get_vault(&input.vault_id).await {
-        Ok(v) => v,
-        Err(_) => {
-            return Err(Box::new(CommandError {
-                code: ErrorCode::KeyNotFound,
-                message: format!("Vault '{}' not found", input.vault_id),
-                details: None,
-                recovery_guidance: None,
-                user_actionable: false,
-                trace_id: None,
-                span_id: None,
-            }));
-        }
-    };
+    let vault = manager
+        .get_vault(&input.vault_id)
+        .await
+        .map_err(load_vault_error)?;
 
     // Check if vault has recipients and force flag is not set
     if !vault.recipients().is_empty() && !input.force {
@@ -192,26 +393,37 @@ pub async fn delete_vault(input: DeleteVaultRequest) -> CommandResponse<DeleteVa
             details: Some(format!("Vault has {} key(s)", vault.recipients().len())),
             recovery_guidance: Some("Remove all keys first or use force=true".to_string()),
             user_actionable: true,
-            trace_id: None,
+            trace_id: current_trace_id(),
             span_id: None,
+            validation: None,
+            help_available: true,
         }));
     }
 
     // Delete the vault using VaultManager
     let manager = VaultManager::new();
     match manager.delete_vault(&input.vault_id, input.force).await {
-        Ok(_) => Ok(DeleteVaultResponse {
-            success: true,
-            message: format!("Vault '{}' deleted successfully", vault.label()),
-        }),
+        Ok(_) => {
+            WindowSessions::global().forget_vault(&input.vault_id);
+            Ok(DeleteVaultResponse {
+                success: true,
+                message: format!("Vault '{}' deleted successfully", vault.label()),
+            })
+        }
+        Err(e @ VaultError::StoreInUseBy { .. }) => Err(Box::new(CommandError::operation(
+            ErrorCode::StoreInUse,
+            e.to_string(),
+        ))),
         Err(e) => Err(Box::new(CommandError {
             code: ErrorCode::StorageFailed,
             message: "Failed to delete vault".to_string(),
             details: Some(e.to_string()),
             recovery_guidance: None,
             user_actionable: false,
-            trace_id: None,
+            trace_id: current_trace_id(),
             span_id: None,
+            validation: None,
+            help_available: true,
         })),
     }
 }
@@ -37,6 +37,69 @@
 /// Bytes per megabyte as float for display formatting
 pub const BYTES_PER_MB_F64: f64 = 1024.0 * 1024.0;
 
+// ============================================================================
+// Upload Metadata Constants
+// ============================================================================
+
+/// Default multipart part size for upload metadata (8MB, matches the AWS CLI default)
+pub const DEFAULT_UPLOAD_PART_SIZE_MB: u64 = 8;
+
+/// Minimum multipart part size accepted by S3-compatible storage (5MB)
+pub const MIN_UPLOAD_PART_SIZE_MB: u64 = 5;
+
+/// Maximum multipart part size accepted by S3-compatible storage (5GB)
+pub const MAX_UPLOAD_PART_SIZE_MB: u64 = 5 * 1024;
+
+// ============================================================================
+// Path Limit Constants
+// ============================================================================
+
+/// Windows MAX_PATH in UTF-16 units, including the terminating NUL
+pub const WINDOWS_MAX_PATH: usize = 260;
+
+/// Windows maximum path length with the `\\?\` extended-length prefix
+pub const WINDOWS_EXTENDED_MAX_PATH: usize = 32_767;
+
+/// macOS PATH_MAX in bytes, including the terminating NUL
+pub const MACOS_MAX_PATH: usize = 1024;
+
+/// Linux PATH_MAX in bytes, including the terminating NUL
+pub const LINUX_MAX_PATH: usize = 4096;
+
+/// Maximum single path component length (255 on NTFS, APFS and ext4)
+pub const MAX_PATH_COMPONENT: usize = 255;
+
+/// Output directory length assumed when checking archive portability
+/// (e.g. `C:\Users\<name>\Documents\Barqly-Recovery\<vault>\`)
+pub const PORTABLE_OUTPUT_DIR_BUDGET: usize = 100;
+
+/// Number of hex characters of the content hash appended to shortened names
+pub const SHORTENED_NAME_HASH_LEN: usize = 8;
+
+/// Bytes read from the start of a file to detect its content type (8KB)
+pub const CONTENT_SNIFF_LIMIT: usize = 8192;
+
+// ============================================================================
+// Archive Safety Limit Constants
+// ============================================================================
+
+/// Maximum number of entries (files and directories) in one archive
+pub const MAX_ARCHIVE_ENTRIES: u64 = 500_000;
+
+/// Maximum number of path components in one archive entry
+pub const MAX_ARCHIVE_PATH_DEPTH: u64 = 128;
+
+/// Maximum uncompressed-to-compressed size ratio of one archive entry
+///
+/// Deflate tops out near 1032:1, so only near-pure runs of one byte hit this.
+pub const MAX_ENTRY_EXPANSION_RATIO: u64 = 500;
+
+/// Entries smaller than this are never checked for their expansion ratio (16MB)
+pub const EXPANSION_RATIO_MIN_ENTRY_SIZE: u64 = 16 * 1024 * 1024;
+
+/// Maximum total uncompressed size of one archive (64GB)
+pub const MAX_ARCHIVE_UNCOMPRESSED_SIZE: u64 = 64 * 1024 * 1024 * 1024;
+
 // ============================================================================
 // Validation Constants
 // ============================================================================
@@ -101,6 +164,9 @@
 /// SHA-256 is used for file integrity checking
 pub const HASH_ALGORITHM: &str = "SHA-256";
 
+/// Entries per shard in a saved archive manifest's index
+pub const MANIFEST_SHARD_ENTRIES: usize = 4096;
+
 // ============================================================================
 // Cache Constants
 // ============================================================================
@@ -126,3 +192,32 @@
 
 /// Progress values that should never be debounced (start/end)
 pub const PROGRESS_IMMEDIATE_EMIT_VALUES: &[f32] = &[0.0, 1.0];
+
+// ============================================================================
+// App Compatibility Constants
+// ============================================================================
+
+/// Version of this build
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Lowest app version that opens an archive using no optional features
+/// (vault manifest schema v2)
+pub const BASELINE_APP_VERSION: &str = "0.2.0";
+
+/// App version that introduced each optional archive feature
+///
+/// Keyed by `ArchiveFeature::as_str()`. Add an entry whenever a release starts
+/// writing something older apps don't understand.
+pub const ARCHIVE_FEATURE_VERSIONS: &[(&str, &str)] = &[
+    ("embedded_manifest", "0.2.2"),
+    ("file_ownership", "0.2.2"),
+    ("skipped_entries", "0.2.2"),
+    ("vault_template", "0.2.2"),
+    ("vault_items", "0.2.2"),
+    ("directory_entries", "0.2.2"),
+    ("file_digests", "0.2.2"),
+    ("raw_file_names", "0.2.2"),
+];
+
+/// Where users get a newer app when an archive needs one
+pub const APP_DOWNLOAD_URL: &str = "https://github.com/barqly/barqly-vault/releases";
@@ -14,6 +14,10 @@ pub enum StorageError {
     #[error("Key not found: {0}")]
     KeyNotFound(String),
 
+    /// Key file is on a removable drive that isn't connected
+    #[error("Key drive '{volume_label}' is not connected")]
+    KeyMediaNotPresent { volume_label: String },
+
     /// Key already exists in storage
     #[error("Key already exists: {0}")]
     KeyAlreadyExists(String),
@@ -0,0 +1,325 @@
+//! Feature flags
+//!
+//! Backend capabilities land incrementally, so the UI asks this build which
+//! features it supports instead of sniffing the version. `REGISTRY` is the
+//! single source of truth for both `get_feature_flags` and the gating checks
+//! in command handlers.
+//!
+//! - **Stable** features are always on.
+//! - **Experimental** features are on in debug builds and off in release
+//!   builds; `BARQLY_FEATURE_<NAME>=1` or `=0` overrides that for development.
+//! - **Disabled** features aren't part of this build and can't be switched on.
+//!
+//! Gated handlers call `require_feature`, which fails with `FeatureDisabled`
+//! instead of letting a half-finished path run.
+
+use crate::types::{CommandError, ErrorCode};
+use serde::{Deserialize, Serialize};
+use std::sync::OnceLock;
+
+/// Prefix of the env vars that override experimental features
+pub const FEATURE_ENV_PREFIX: &str = "BARQLY_FEATURE_";
+
+/// A capability the UI may need to adapt to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, specta::Type)]
+#[serde(rename_all = "snake_case")]
+pub enum FeatureFlag {
+    /// Content previews during vault analysis
+    ArchivePreviews,
+    /// Comparing a local folder against a vault's file list
+    DirectoryComparison,
+    /// zstd compression for new archives
+    ZstdCompression,
+    /// Splitting large archives into fixed-size volumes
+    SplitArchives,
+    /// Vaults hidden from the vault list
+    HiddenVaults,
+    /// Simulated YubiKey for development without hardware
+    MockYubiKey,
+}
+
+impl FeatureFlag {
+    /// Env var overriding this flag, e.g. `BARQLY_FEATURE_ARCHIVE_PREVIEWS`
+    pub fn env_var(&self) -> String {
+        let name = serde_json::to_value(self)
+            .ok()
+            .and_then(|v| v.as_str().map(str::to_uppercase))
+            .unwrap_or_default();
+        format!("{FEATURE_ENV_PREFIX}{name}")
+    }
+}
+
+/// How far a feature has shipped
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+#[serde(rename_all = "snake_case")]
+pub enum FeatureStatus {
+    Stable,
+    Experimental,
+    /// Not part of this build
+    Disabled,
+}
+
+/// A registry entry
+#[derive(Debug, Clone, Copy)]
+pub struct FeatureDefinition {
+    pub flag: FeatureFlag,
+    pub status: FeatureStatus,
+    pub description: &'static str,
+}
+
+/// Every feature flag and its status in this build
+pub const REGISTRY: &[FeatureDefinition] = &[
+    FeatureDefinition {
+        flag: FeatureFlag::ArchivePreviews,
+        status: FeatureStatus::Experimental,
+        description: "Preview archive contents during vault analysis",
+    },
+    FeatureDefinition {
+        flag: FeatureFlag::DirectoryComparison,
+        status: FeatureStatus::Experimental,
+        description: "Compare a local folder against a vault's file list",
+    },
+    FeatureDefinition {
+        flag: FeatureFlag::ZstdCompression,
+        status: FeatureStatus::Disabled,
+        description: "zstd compression for new archives",
+    },
+    FeatureDefinition {
+        flag: FeatureFlag::SplitArchives,
+        status: FeatureStatus::Disabled,
+        description: "Split large archives into fixed-size volumes",
+    },
+    FeatureDefinition {
+        flag: FeatureFlag::HiddenVaults,
+        status: FeatureStatus::Disabled,
+        description: "Hide vaults from the vault list",
+    },
+    FeatureDefinition {
+        flag: FeatureFlag::MockYubiKey,
+        status: FeatureStatus::Disabled,
+        description: "Simulated YubiKey for development without hardware",
+    },
+];
+
+/// Command handlers gated on an experimental flag
+///
+/// Checked by a test so a gated path can't lose its `require_feature` call.
+pub const GATED_COMMANDS: &[(FeatureFlag, &str)] = &[
+    (FeatureFlag::ArchivePreviews, "analyze_encrypted_vault"),
+    (
+        FeatureFlag::DirectoryComparison,
+        "compare_vault_to_directory",
+    ),
+];
+
+/// A flag's resolved state, as reported to the UI
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, specta::Type)]
+pub struct FeatureFlagState {
+    pub flag: FeatureFlag,
+    pub status: FeatureStatus,
+    pub enabled: bool,
+    /// Enabled state came from an env var override
+    pub overridden: bool,
+    pub description: String,
+}
+
+/// Resolved state of every flag
+#[derive(Debug, Clone)]
+pub struct FeatureFlags {
+    states: Vec<FeatureFlagState>,
+}
+
+impl FeatureFlags {
+    /// Resolve flags from an environment lookup
+    ///
+    /// `experimental_default` is whether experimental features are on when
+    /// no override is set.
+    pub fn resolve(env: impl Fn(&str) -> Option<String>, experimental_default: bool) -> Self {
+        let states = REGISTRY
+            .iter()
+            .map(|definition| {
+                let requested = match definition.status {
+                    FeatureStatus::Experimental => {
+                        parse_override(env(&definition.flag.env_var()).as_deref())
+                    }
+                    FeatureStatus::Stable | FeatureStatus::Disabled => None,
+                };
+                let enabled = match definition.status {
+                    FeatureStatus::Stable => true,
+                    FeatureStatus::Experimental => requested.unwrap_or(experimental_default),
+                    FeatureStatus::Disabled => false,
+                };
+                FeatureFlagState {
+                    flag: definition.flag,
+                    status: definition.status,
+                    enabled,
+                    overridden: requested.is_some(),
+                    description: definition.description.to_string(),
+                }
+            })
+            .collect();
+        Self { states }
+    }
+
+    /// Flags for this process, resolved once from the environment
+    pub fn current() -> &'static Self {
+        static CURRENT: OnceLock<FeatureFlags> = OnceLock::new();
+        CURRENT
+            .get_or_init(|| Self::resolve(|name| std::env::var(name).ok(), cfg!(debug_assertions)))
+    }
+
+    pub fn states(&self) -> &[FeatureFlagState] {
+        &self.states
+    }
+
+    pub fn is_enabled(&self, flag: FeatureFlag) -> bool {
+        self.states
+            .iter()
+            .any(|state| state.flag == flag && state.enabled)
+    }
+
+    /// Fail with `FeatureDisabled` unless `flag` is on
+    pub fn require(&self, flag: FeatureFlag) -> Result<(), Box<CommandError>> {
+        if self.is_enabled(flag) {
+            return Ok(());
+        }
+        let state = self.states.iter().find(|state| state.flag == flag);
+        let details = match state.map(|state| state.status) {
+            Some(FeatureStatus::Experimental) => format!(
+                "Experimental feature is switched off; set {}=1 to enable it",
+                flag.env_var()
+            ),
+            _ => "Not part of this build".to_string(),
+        };
+        Err(Box::new(
+            CommandError::operation(
+                ErrorCode::FeatureDisabled,
+                format!(
+                    "{} is not available",
+                    state.map_or("This feature", |state| state.description.as_str())
+                ),
+            )
+            .with_details(details),
+        ))
+    }
+}
+
+/// Fail with `FeatureDisabled` unless `flag` is on in this process
+pub fn require_feature(flag: FeatureFlag) -> Result<(), Box<CommandError>> {
+    FeatureFlags::current().require(flag)
+}
+
+fn parse_override(value: Option<&str>) -> Option<bool> {
+    match value?.trim().to_ascii_lowercase().as_str() {
+        "1" | "true" | "on" | "yes" => Some(true),
+        "0" | "false" | "off" | "no" => Some(false),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    fn no_env(_: &str) -> Option<String> {
+        None
+    }
+
+    #[test]
+    fn test_registry_lists_every_flag_once() {
+        let flags: HashSet<FeatureFlag> = REGISTRY.iter().map(|d| d.flag).collect();
+        assert_eq!(flags.len(), REGISTRY.len());
+    }
+
+    #[test]
+    fn test_defaults_follow_status() {
+        let flags = FeatureFlags::resolve(no_env, false);
+        assert!(!flags.is_enabled(FeatureFlag::DirectoryComparison));
+        assert!(!flags.is_enabled(FeatureFlag::ZstdCompression));
+
+        let flags = FeatureFlags::resolve(no_env, true);
+        assert!(flags.is_enabled(FeatureFlag::DirectoryComparison));
+        assert!(!flags.is_enabled(FeatureFlag::MockYubiKey));
+    }
+
+    #[test]
+    fn test_env_overrides_experimental_flags_only() {
+        let env = |name: &str| {
+            matches!(
+                name,
+                "BARQLY_FEATURE_ARCHIVE_PREVIEWS" | "BARQLY_FEATURE_HIDDEN_VAULTS"
+            )
+            .then(|| "1".to_string())
+        };
+        let flags = FeatureFlags::resolve(env, false);
+        assert!(flags.is_enabled(FeatureFlag::ArchivePreviews));
+        assert!(!flags.is_enabled(FeatureFlag::HiddenVaults));
+
+        let state = flags
+            .states()
+            .iter()
+            .find(|s| s.flag == FeatureFlag::ArchivePreviews)
+            .unwrap();
+        assert!(state.overridden);
+
+        let off = |name: &str| (name == "BARQLY_FEATURE_ARCHIVE_PREVIEWS").then(|| "0".to_string());
+        assert!(!FeatureFlags::resolve(off, true).is_enabled(FeatureFlag::ArchivePreviews));
+    }
+
+    #[test]
+    fn test_require_reports_feature_disabled() {
+        let flags = FeatureFlags::resolve(no_env, false);
+        let error = flags.require(FeatureFlag::SplitArchives).unwrap_err();
+        assert!(matches!(error.code, ErrorCode::FeatureDisabled));
+        assert_eq!(error.details.as_deref(), Some("Not part of this build"));
+
+        let error = flags.require(FeatureFlag::ArchivePreviews).unwrap_err();
+        assert!(
+            error
+                .details
+                .unwrap()
+                .contains("BARQLY_FEATURE_ARCHIVE_PREVIEWS=1")
+        );
+    }
+
+    /// Source of each module holding a gated command handler
+    const HANDLER_SOURCES: &[&str] = &[
+        include_str!("commands/crypto/vault_analysis.rs"),
+        include_str!("commands/vault/directory_comparison.rs"),
+    ];
+
+    /// Body of `fn name(` up to the closing brace at column 0
+    fn handler_body(name: &str) -> Option<&'static str> {
+        let signature = format!("fn {name}(");
+        HANDLER_SOURCES.iter().find_map(|source| {
+            let start = source.find(&signature)?;
+            let body = &source[start..];
+            Some(&body[..body.find("\n}\n").unwrap_or(body.len())])
+        })
+    }
+
+    #[test]
+    fn test_every_experimental_flag_gates_its_handlers() {
+        for definition in REGISTRY {
+            let gated = GATED_COMMANDS
+                .iter()
+                .any(|(flag, _)| *flag == definition.flag);
+            assert_eq!(
+                gated,
+                definition.status == FeatureStatus::Experimental,
+                "{:?} is {:?} but {} in GATED_COMMANDS",
+                definition.flag,
+                definition.status,
+                if gated { "listed" } else { "not listed" }
+            );
+        }
+
+        for (flag, handler) in GATED_COMMANDS {
+            let body = handler_body(handler)
+                .unwrap_or_else(|| panic!("handler {handler} not found in HANDLER_SOURCES"));
+            let check = format!("require_feature(FeatureFlag::{flag:?})");
+            assert!(body.contains(&check), "{handler} must call {check}");
+        }
+    }
+}
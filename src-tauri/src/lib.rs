@@ -9,6 +9,7 @@
 pub mod constants; // Centralized constants for the application
 // Crypto module moved to services/crypto/infrastructure for proper DDD architecture
 pub mod error; // Centralized error handling infrastructure
+pub mod features; // Feature flags reported to the UI and checked by gated commands
 pub mod logging; // Centralized logging and tracing infrastructure
 pub mod prelude;
 pub mod services; // Business logic layer (DDD) - renamed from key_management
@@ -16,27 +17,61 @@
 
 use commands::{
     analyze_encrypted_vault,
+    assess_salvage,
+    browse_archive,
+    cancel_cleanup_session,
+    check_decryption_key,
+    compute_upload_metadata,
+    confirm_deep_link,
+    copy_to_clipboard,
     create_manifest,
+    decrypt_batch,
     decrypt_data,
+    decrypt_with_recovery_shares,
+    dismiss_deep_link,
     encrypt_files,
     encrypt_files_multi,
+    expire_launch_watchdog,
+    export_launch_diagnostics,
+    extend_cleanup_session,
+    frontend_ready,
+    get_benchmark_history,
+    get_clipboard_guard_status,
+    get_decrypt_options,
+    get_diagnostics,
     // Crypto commands
     get_encryption_status,
+    get_error_help,
+    get_feature_flags,
     get_file_info,
+    get_indexing_protection_status,
     // Key management commands
     get_key_menu_data,
+    get_logs_for_trace,
+    get_pending_deep_link,
     get_progress,
+    get_secure_delete_capability,
+    // Storage commands
+    get_storage_paths,
+    get_storage_usage,
     key_management::{
         add_recipient::add_recipient,
         attach_key::attach_key_to_vault,
+        contacts::{add_contact, list_contacts, remove_contact},
         deactivate_key::deactivate_key,
         delete_key::delete_key,
         export_key::export_key,
         import_key::import_key_file,
+        key_removal_plan::plan_key_removal,
+        legacy_migration::get_legacy_migration_report,
+        normalize_key_labels::normalize_key_labels,
         passphrase::{
-            add_passphrase_key_to_vault, generate_key, validate_passphrase,
-            validate_passphrase_strength, validate_vault_passphrase_key, verify_key_passphrase,
+            add_passphrase_key_to_vault, create_recovery_shares, generate_key, repair_key_file,
+            validate_key_file, validate_passphrase, validate_passphrase_strength,
+            validate_vault_passphrase_key, verify_key_passphrase,
         },
+        relink_key_file::relink_key_file,
+        replace_yubikey::replace_yubikey,
         restore_key::restore_key,
         unified_keys::{
             get_vault_keys, list_unified_keys, remove_key_from_vault, test_unified_keys,
@@ -44,25 +79,65 @@
         },
         update_global_key_label::update_global_key_label,
         yubikey::{
-            complete_yubikey_setup, generate_yubikey_identity, init_yubikey,
-            init_yubikey_for_vault, list_yubikeys, register_yubikey, register_yubikey_for_vault,
+            complete_yubikey_setup, generate_yubikey_identity, get_plugin_protocol_info,
+            get_pty_sessions, init_yubikey, init_yubikey_for_vault, kill_pty_session,
+            list_yubikeys, register_yubikey, register_yubikey_for_vault, reserve_yubikey_slot,
             yubikey_decrypt_file,
         },
     },
-    // Storage commands
+    list_cleanup_sessions,
+    migrate_archive,
+    panic_lock,
+    plan_parameter_migration,
+    preview_panic_lock,
+    record_page_load,
+    record_window_closed,
+    regenerate_external_manifest,
+    register_deep_link_handler,
+    remap_archive_paths,
+    run_benchmark,
+    salvage_decrypt,
     select_directory,
     // File commands
     select_files,
+    set_clipboard_settings,
+    set_default_io_priority,
+    set_operation_priority,
+    set_storage_quotas,
+    simulate_recovery,
+    start_cleanup_scheduler,
+    start_launch_watchdog,
+    stop_browsing,
     // Vault commands
     vault::{
-        create_vault, delete_vault, get_all_vault_statistics, get_current_vault,
-        get_vault_statistics, list_vaults, set_current_vault,
+        add_vault_item, assess_vault_risk, check_in, compare_vault_to_directory, create_vault,
+        delete_vault, diff_metadata_snapshot, dismiss_notification, evaluate_retention,
+        export_inventory, force_quit_and_abort, get_all_vault_statistics,
+        get_compatibility_changes, get_current_vault, get_dead_mans_switch_status,
+        get_default_vault, get_last_maintenance_report, get_notification_preferences,
+        get_notifications, get_onboarding_status, get_protection_status, get_shared_store_status,
+        get_shutdown_status, get_vault_hooks, get_vault_statistics, get_vault_statistics_history,
+        get_vault_transforms, list_archives, list_metadata_snapshots, list_vault_items,
+        list_vault_templates, list_vaults, patch_archive, prepare_eject, prune_archives,
+        purge_quarantine, record_app_start, remove_vault_item, reorder_vaults, repair_archive,
+        resolve_vault_conflict, restore_metadata_snapshot, run_maintenance,
+        scan_for_incomplete_archives, search_archives, search_files, set_allow_pending_yubikeys,
+        set_archive_immutable, set_cross_vault_name_policy, set_current_vault,
+        set_dead_mans_switch, set_retention_policy, set_shared_store, set_vault_favorite,
+        take_over_shared_store, test_hook, update_archive_comment, update_notification_preferences,
+        update_vault_hooks, update_vault_item, update_vault_transforms, verify_operation_log,
     },
     verify_manifest,
+    verify_tool_independence,
 };
 
 use crate::prelude::*;
-use services::vault::application::services::BootstrapService;
+use services::shared::infrastructure::io::RecoveryAction;
+use services::vault::VaultManager;
+use services::vault::application::services::{
+    BootstrapService, SHUTDOWN_BLOCKED_EVENT, exit_allowed,
+};
+use services::vault::domain::models::ShutdownCheck;
 
 /// Run bootstrap initialization
 ///
@@ -79,6 +154,12 @@ fn run_bootstrap() -> Result<(), Box<dyn std::error::Error>> {
             manifests_found = result.manifests_found,
             keys_added = result.keys_added,
             keys_total = result.keys_after,
+            journal_replayed = result.journal_recovery.count(RecoveryAction::Replayed),
+            journal_rolled_back = result.journal_recovery.count(RecoveryAction::RolledBack),
+            labels_renamed = result.label_renames.len(),
+            vaults_with_incomplete_archives = result.incomplete_archives.len(),
+            interrupted_operations = result.interrupted_operations.len(),
+            storage_items_purged = result.storage_cleanup.purged.len(),
             "Bootstrap completed"
         );
 
@@ -86,28 +167,81 @@ fn run_bootstrap() -> Result<(), Box<dyn std::error::Error>> {
     })
 }
 
-/// Generate TypeScript bindings for all Tauri commands
-/// This is called by the generate-bindings binary and the build hooks
-pub fn generate_typescript_bindings() -> Result<(), String> {
-    use specta_typescript::Typescript;
-    use std::fs;
-    use std::path::Path;
+/// What a quit requested now would interrupt, unless it is safe or a
+/// forced quit already ran
+fn blocked_quit() -> Option<ShutdownCheck> {
+    if exit_allowed() {
+        return None;
+    }
+    let check = VaultManager::new().get_shutdown_status();
+    (!check.safe_to_quit).then_some(check)
+}
+
+/// Version of the generated bindings contract, written into the bindings header
+///
+/// Bump whenever a command's request or response shape or units change.
+/// 2: sizes are `ByteSize` (bytes) and durations `DurationMs` (milliseconds).
+pub const BINDINGS_VERSION: u32 = 2;
+
+/// Every command in the generated bindings
+fn bindings_builder() -> tauri_specta::Builder<tauri::Wry> {
     use tauri_specta::{Builder, collect_commands};
 
-    let builder = Builder::<tauri::Wry>::new().commands(collect_commands![
+    Builder::<tauri::Wry>::new().commands(collect_commands![
         // Crypto commands
         generate_key,
+        create_recovery_shares,
         validate_passphrase,
         verify_key_passphrase,
         validate_passphrase_strength,
         encrypt_files,
         encrypt_files_multi,
+        get_pending_deep_link,
+        confirm_deep_link,
+        dismiss_deep_link,
         get_encryption_status,
         decrypt_data,
+        list_cleanup_sessions,
+        extend_cleanup_session,
+        cancel_cleanup_session,
+        preview_panic_lock,
+        panic_lock,
+        check_decryption_key,
+        get_decrypt_options,
+        decrypt_with_recovery_shares,
+        assess_salvage,
+        salvage_decrypt,
+        simulate_recovery,
+        decrypt_batch,
+        browse_archive,
+        stop_browsing,
+        plan_parameter_migration,
+        migrate_archive,
+        remap_archive_paths,
+        verify_tool_independence,
+        regenerate_external_manifest,
         verify_manifest,
         get_progress,
+        set_operation_priority,
+        set_default_io_priority,
         analyze_encrypted_vault,
+        run_benchmark,
+        get_benchmark_history,
         // Storage commands
+        get_storage_paths,
+        get_secure_delete_capability,
+        get_storage_usage,
+        get_indexing_protection_status,
+        set_storage_quotas,
+        get_diagnostics,
+        get_feature_flags,
+        get_error_help,
+        get_logs_for_trace,
+        frontend_ready,
+        export_launch_diagnostics,
+        copy_to_clipboard,
+        get_clipboard_guard_status,
+        set_clipboard_settings,
         // Unified key management
         list_unified_keys,
         test_unified_keys,
@@ -116,53 +250,141 @@ pub fn generate_typescript_bindings() -> Result<(), String> {
         remove_key_from_vault,
         update_key_label,
         // Key lifecycle management
+        plan_key_removal,
         deactivate_key,
         delete_key,
         export_key,
         restore_key,
         update_global_key_label,
+        normalize_key_labels,
+        get_legacy_migration_report,
+        relink_key_file,
+        replace_yubikey,
         // File commands
         select_files,
         select_directory,
         get_file_info,
         create_manifest,
+        compute_upload_metadata,
         // Vault commands
         create_vault,
         list_vaults,
+        set_vault_favorite,
+        reorder_vaults,
+        get_shared_store_status,
+        set_shared_store,
+        take_over_shared_store,
+        resolve_vault_conflict,
         get_current_vault,
+        get_default_vault,
         set_current_vault,
         delete_vault,
         get_vault_statistics,
+        get_vault_statistics_history,
         get_all_vault_statistics,
+        list_vault_templates,
+        get_protection_status,
+        set_allow_pending_yubikeys,
+        assess_vault_risk,
+        get_notifications,
+        get_onboarding_status,
+        get_compatibility_changes,
+        dismiss_notification,
+        get_notification_preferences,
+        update_notification_preferences,
+        get_dead_mans_switch_status,
+        set_dead_mans_switch,
+        check_in,
+        get_vault_hooks,
+        update_vault_hooks,
+        test_hook,
+        get_vault_transforms,
+        update_vault_transforms,
+        get_shutdown_status,
+        force_quit_and_abort,
+        prepare_eject,
+        verify_operation_log,
+        search_archives,
+        search_files,
+        update_archive_comment,
+        list_archives,
+        set_archive_immutable,
+        repair_archive,
+        patch_archive,
+        set_cross_vault_name_policy,
+        set_retention_policy,
+        evaluate_retention,
+        prune_archives,
+        add_vault_item,
+        update_vault_item,
+        remove_vault_item,
+        list_vault_items,
+        run_maintenance,
+        get_last_maintenance_report,
+        scan_for_incomplete_archives,
+        purge_quarantine,
+        compare_vault_to_directory,
+        export_inventory,
+        restore_metadata_snapshot,
+        diff_metadata_snapshot,
+        list_metadata_snapshots,
         // Passphrase/YubiKey vault integration
         add_passphrase_key_to_vault,
         validate_vault_passphrase_key,
         init_yubikey_for_vault,
         register_yubikey_for_vault,
+        reserve_yubikey_slot,
         attach_key_to_vault,
         import_key_file,
+        validate_key_file,
+        repair_key_file,
         // Recipient (public-key-only) commands
         add_recipient,
+        // Contact commands
+        add_contact,
+        list_contacts,
+        remove_contact,
         // Streamlined YubiKey commands
         list_yubikeys,
+        get_pty_sessions,
+        kill_pty_session,
+        get_plugin_protocol_info,
         init_yubikey,
         complete_yubikey_setup,
         generate_yubikey_identity,
         register_yubikey,
         // YubiKey crypto commands
         yubikey_decrypt_file,
-    ]);
+    ])
+}
+
+/// Generator settings for the TypeScript bindings
+fn bindings_language() -> specta_typescript::Typescript {
+    specta_typescript::Typescript::default()
+        .bigint(specta_typescript::BigIntExportBehavior::Number)
+        .header(format!(
+            "// This file is auto-generated by tauri-specta. Do not edit manually.\n\
+             // Bindings version: {BINDINGS_VERSION}"
+        ))
+}
+
+/// Generate TypeScript bindings for all Tauri commands
+/// This is called by the generate-bindings binary and the build hooks
+///
+/// The file ends with `BINDINGS_HASH`, which the interface sends in its
+/// `frontend_ready` handshake.
+pub fn generate_typescript_bindings() -> Result<(), String> {
+    use services::vault::domain::models::{
+        BINDINGS_HASH_EXPORT, hash_bindings, normalize_bindings,
+    };
+    use std::fs;
+    use std::path::Path;
 
     let bindings_path = "../src-ui/src/bindings.ts";
 
     // First, export the bindings
-    builder
-        .export(
-            Typescript::default()
-                .bigint(specta_typescript::BigIntExportBehavior::Number)
-                .header("// This file is auto-generated by tauri-specta. Do not edit manually."),
-            bindings_path,
-        )
+    bindings_builder()
+        .export(bindings_language(), bindings_path)
         .map_err(|e| format!("Failed to export TypeScript bindings: {e}"))?;
 
     // Post-process the file to add @ts-nocheck at the very beginning
@@ -170,18 +392,15 @@ pub fn generate_typescript_bindings() -> Result<(), String> {
     let content = fs::read_to_string(&bindings_full_path)
         .map_err(|e| format!("Failed to read bindings file: {e}"))?;
 
-    // Remove any existing @ts-nocheck comments to avoid duplicates
-    let lines: Vec<&str> = content.lines().collect();
-    let content_without_ts_nocheck = lines
-        .into_iter()
-        .filter(|line| !line.contains("@ts-nocheck"))
-        .collect::<Vec<_>>()
-        .join("\n");
+    // Remove any existing @ts-nocheck comments and hash to avoid duplicates
+    let generated = normalize_bindings(&content);
 
     // Prepend @ts-nocheck to suppress TypeScript warnings for unused generated code
     let modified_content = format!(
-        "// @ts-nocheck - Suppress TypeScript warnings for unused generated code\n{}",
-        content_without_ts_nocheck
+        "// @ts-nocheck - Suppress TypeScript warnings for unused generated code\n{}\n\n{} = \"{}\";\n",
+        generated,
+        BINDINGS_HASH_EXPORT,
+        hash_bindings(&generated)
     );
 
     fs::write(&bindings_full_path, modified_content)
@@ -190,6 +409,29 @@ pub fn generate_typescript_bindings() -> Result<(), String> {
     Ok(())
 }
 
+/// Hash of the bindings this build generates, matching the `BINDINGS_HASH`
+/// of an interface built against them
+///
+/// Rendered once through a temporary file, then cached.
+pub fn current_bindings_hash() -> Result<String, String> {
+    use services::vault::domain::models::hash_bindings;
+    use std::sync::OnceLock;
+
+    static HASH: OnceLock<Result<String, String>> = OnceLock::new();
+    HASH.get_or_init(|| {
+        let temp_dir =
+            tempfile::tempdir().map_err(|e| format!("Failed to create a temp folder: {e}"))?;
+        let path = temp_dir.path().join("bindings.ts");
+        bindings_builder()
+            .export(bindings_language(), &path)
+            .map_err(|e| format!("Failed to render TypeScript bindings: {e}"))?;
+        let content = std::fs::read_to_string(&path)
+            .map_err(|e| format!("Failed to read rendered bindings: {e}"))?;
+        Ok(hash_bindings(&content))
+    })
+    .clone()
+}
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run_app() {
     // CRITICAL: Initialize PathProvider FIRST (before logging)
@@ -216,6 +458,12 @@ pub fn run_app() {
     // Use tracing for application started message
     info!("Barqly Vault application started");
 
+    // Surfaced now that logging is up; PathProvider resolves before it
+    if let Some(warning) = services::shared::infrastructure::path_management::get_profile_warning()
+    {
+        warn!(%warning, "Existing profile not used in portable mode");
+    }
+
     // Run bootstrap to sync registry from vault manifests
     if let Err(e) = run_bootstrap() {
         warn!(error = %e, "Bootstrap failed, continuing with startup");
@@ -225,6 +473,8 @@ pub fn run_app() {
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_dialog::init())
+        .plugin(tauri_plugin_deep_link::init())
+        .plugin(tauri_plugin_clipboard_manager::init())
         .setup(|app| {
             // Initialize the global AppHandle for binary path resolution
             use services::key_management::yubikey::infrastructure::pty::app_handle::init_app_handle;
@@ -239,22 +489,100 @@ pub fn run_app() {
                 warn!("Failed to update PathProvider with AppHandle: {}", e);
             }
 
+            // Record this version and announce vault-format changes after an upgrade
+            record_app_start();
+
+            // Files sent from Finder Services arrive as barqly-vault:// links
+            register_deep_link_handler(app);
+
+            // Delete decrypted output whose session TTL has passed
+            start_cleanup_scheduler();
+
+            // Record the launch if the interface doesn't load in time
+            start_launch_watchdog();
+
             Ok(())
         })
+        .on_page_load(|webview, payload| {
+            let finished = matches!(payload.event(), tauri::webview::PageLoadEvent::Finished);
+            record_page_load(webview.label(), payload.url().as_str(), finished);
+        })
+        .on_window_event(|window, event| match event {
+            // Closing the last window quits; hold it back while work is running
+            tauri::WindowEvent::CloseRequested { api, .. } => {
+                use tauri::{Emitter, Manager};
+                if window.app_handle().webview_windows().len() <= 1
+                    && let Some(check) = blocked_quit()
+                {
+                    api.prevent_close();
+                    if let Err(e) = window.emit(SHUTDOWN_BLOCKED_EVENT, &check) {
+                        warn!(error = %e, "Failed to emit shutdown-blocked event");
+                    }
+                }
+            }
+            // A closed window's current-vault pointer must not become the default
+            tauri::WindowEvent::Destroyed => {
+                services::shared::infrastructure::WindowSessions::global()
+                    .close_window(window.label());
+                record_window_closed(window.label());
+            }
+            _ => {}
+        })
         .invoke_handler(tauri::generate_handler![
             // Crypto commands
             generate_key,
+            create_recovery_shares,
             validate_passphrase,
             verify_key_passphrase,
             validate_passphrase_strength,
             encrypt_files,
             encrypt_files_multi,
+            get_pending_deep_link,
+            confirm_deep_link,
+            dismiss_deep_link,
             get_encryption_status,
             decrypt_data,
+            list_cleanup_sessions,
+            extend_cleanup_session,
+            cancel_cleanup_session,
+            preview_panic_lock,
+            panic_lock,
+            check_decryption_key,
+            get_decrypt_options,
+            decrypt_with_recovery_shares,
+            assess_salvage,
+            salvage_decrypt,
+            simulate_recovery,
+            decrypt_batch,
+            browse_archive,
+            stop_browsing,
+            plan_parameter_migration,
+            migrate_archive,
+            remap_archive_paths,
+            verify_tool_independence,
+            regenerate_external_manifest,
             verify_manifest,
             get_progress,
+            set_operation_priority,
+            set_default_io_priority,
             analyze_encrypted_vault,
+            run_benchmark,
+            get_benchmark_history,
             // Storage commands
+            get_storage_paths,
+            get_secure_delete_capability,
+            get_storage_usage,
+            get_indexing_protection_status,
+            set_storage_quotas,
+            get_diagnostics,
+            get_feature_flags,
+            get_error_help,
+            get_logs_for_trace,
+            frontend_ready,
+            export_launch_diagnostics,
+            copy_to_clipboard,
+            get_clipboard_guard_status,
+            set_clipboard_settings,
             // Unified key management
             list_unified_keys,
             test_unified_keys,
@@ -263,35 +591,105 @@ pub fn run_app() {
             remove_key_from_vault,
             update_key_label,
             // Key lifecycle management
+            plan_key_removal,
             deactivate_key,
             delete_key,
             export_key,
             restore_key,
             update_global_key_label,
+            normalize_key_labels,
+            get_legacy_migration_report,
+            relink_key_file,
+            replace_yubikey,
             // File commands
             select_files,
             select_directory,
             get_file_info,
             create_manifest,
+            compute_upload_metadata,
             // Vault commands
             create_vault,
             list_vaults,
+            set_vault_favorite,
+            reorder_vaults,
+            get_shared_store_status,
+            set_shared_store,
+            take_over_shared_store,
+            resolve_vault_conflict,
             get_current_vault,
+            get_default_vault,
             set_current_vault,
             delete_vault,
             get_vault_statistics,
+            get_vault_statistics_history,
             get_all_vault_statistics,
+            list_vault_templates,
+            get_protection_status,
+            set_allow_pending_yubikeys,
+            assess_vault_risk,
+            get_notifications,
+            get_onboarding_status,
+            get_compatibility_changes,
+            dismiss_notification,
+            get_notification_preferences,
+            update_notification_preferences,
+            get_dead_mans_switch_status,
+            set_dead_mans_switch,
+            check_in,
+            get_vault_hooks,
+            update_vault_hooks,
+            test_hook,
+            get_vault_transforms,
+            update_vault_transforms,
+            get_shutdown_status,
+            force_quit_and_abort,
+            prepare_eject,
+            verify_operation_log,
+            search_archives,
+            search_files,
+            update_archive_comment,
+            list_archives,
+            set_archive_immutable,
+            repair_archive,
+            patch_archive,
+            set_cross_vault_name_policy,
+            set_retention_policy,
+            evaluate_retention,
+            prune_archives,
+            add_vault_item,
+            update_vault_item,
+            remove_vault_item,
+            list_vault_items,
+            run_maintenance,
+            get_last_maintenance_report,
+            scan_for_incomplete_archives,
+            purge_quarantine,
+            compare_vault_to_directory,
+            export_inventory,
+            restore_metadata_snapshot,
+            diff_metadata_snapshot,
+            list_metadata_snapshots,
             // Passphrase/YubiKey vault integration
             add_passphrase_key_to_vault,
             validate_vault_passphrase_key,
             init_yubikey_for_vault,
             register_yubikey_for_vault,
+            reserve_yubikey_slot,
             attach_key_to_vault,
             import_key_file,
+            validate_key_file,
+            repair_key_file,
             // Recipient (public-key-only) commands
             add_recipient,
+            // Contact commands
+            add_contact,
+            list_contacts,
+            remove_contact,
             // Streamlined YubiKey commands
             list_yubikeys,
+            get_pty_sessions,
+            kill_pty_session,
+            get_plugin_protocol_info,
             init_yubikey,
             register_yubikey,
             complete_yubikey_setup,
@@ -299,6 +697,22 @@ pub fn run_app() {
             // YubiKey crypto commands
             yubikey_decrypt_file,
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app, event| {
+            // Quit from the menu or the OS (e.g. logging out on macOS)
+            if let tauri::RunEvent::ExitRequested { api, .. } = &event
+                && let Some(check) = blocked_quit()
+            {
+                use tauri::Emitter;
+                api.prevent_exit();
+                if let Err(e) = app.emit(SHUTDOWN_BLOCKED_EVENT, &check) {
+                    warn!(error = %e, "Failed to emit shutdown-blocked event");
+                }
+            }
+            // Quitting before the interface loaded ends the launch as failed
+            if matches!(event, tauri::RunEvent::Exit) {
+                expire_launch_watchdog();
+            }
+        });
 }
@@ -1,7 +1,9 @@
 //! Custom formatter for Barqly Vault tracing output
 //!
-//! Implements a pipe-separated format with file location information
+//! Implements a pipe-separated format with file location information and,
+//! for events under a command, its trace ID (`trace=<id>`)
 
+use super::trace_context::trace_id_of;
 use std::fmt;
 use tracing::field::{Field, Visit};
 use tracing::{Event, Level, Subscriber};
@@ -153,7 +155,12 @@ fn format_event(
             write!(writer, "{message}")?;
         }
 
-        // 5. Write span context only for DEBUG/TRACE (too noisy for INFO/WARN/ERROR)
+        // 5. Trace ID of the command the event belongs to, at every level
+        if let Some(trace_id) = ctx.parent_span().and_then(|span| trace_id_of(&span)) {
+            write!(writer, " | trace={trace_id}")?;
+        }
+
+        // 6. Write span context only for DEBUG/TRACE (too noisy for INFO/WARN/ERROR)
         if matches!(*level, Level::DEBUG | Level::TRACE)
             && let Some(scope) = ctx.event_scope()
         {
@@ -173,7 +180,7 @@ fn format_event(
             }
         }
 
-        // 6. Write additional fields if present
+        // 7. Write additional fields if present
         if !collector.fields.is_empty() {
             write!(writer, " | ")?;
             let field_strs: Vec<String> = collector
@@ -0,0 +1,128 @@
+//! Recent log reader
+//!
+//! Pulls one command's lines out of the tail of the app log by the trace ID
+//! the formatter writes on them. Only the end of the file is read, so a long
+//! log doesn't make the lookup slow; older invocations may no longer be found.
+
+use super::trace_context::TraceId;
+use super::{LOG_FILE_NAME, get_log_dir};
+use serde::Serialize;
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::Path;
+
+/// How much of the end of the log is searched
+const TAIL_BYTES: u64 = 8 * 1024 * 1024;
+
+/// Log lines written under one trace ID, oldest first
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, specta::Type)]
+pub struct TraceLogLines {
+    pub trace_id: String,
+    pub lines: Vec<String>,
+    /// More lines matched than were returned; the latest are kept
+    pub truncated: bool,
+}
+
+/// Lines of the app log written under `trace_id`, at most `max_lines`
+pub fn read_trace_lines(trace_id: &str, max_lines: usize) -> io::Result<TraceLogLines> {
+    read_trace_lines_from(&get_log_dir()?.join(LOG_FILE_NAME), trace_id, max_lines)
+}
+
+pub fn read_trace_lines_from(
+    path: &Path,
+    trace_id: &str,
+    max_lines: usize,
+) -> io::Result<TraceLogLines> {
+    if !TraceId::is_valid(trace_id) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("'{trace_id}' is not a trace ID"),
+        ));
+    }
+
+    let tail = match read_tail(path) {
+        Ok(tail) => tail,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(e),
+    };
+    let marker = format!(" | trace={trace_id}");
+    let mut lines: Vec<String> = tail
+        .lines()
+        .filter(|line| line.contains(&marker))
+        .map(str::to_string)
+        .collect();
+
+    let truncated = lines.len() > max_lines;
+    if truncated {
+        lines.drain(..lines.len() - max_lines);
+    }
+    Ok(TraceLogLines {
+        trace_id: trace_id.to_string(),
+        lines,
+        truncated,
+    })
+}
+
+/// The last `TAIL_BYTES` of the file, starting at a line boundary
+fn read_tail(path: &Path) -> io::Result<String> {
+    let mut file = File::open(path)?;
+    let len = file.metadata()?.len();
+    let start = len.saturating_sub(TAIL_BYTES);
+    file.seek(SeekFrom::Start(start))?;
+
+    let mut bytes = Vec::new();
+    file.read_to_end(&mut bytes)?;
+    let text = String::from_utf8_lossy(&bytes);
+    Ok(match (start > 0, text.find('\n')) {
+        // Drop the partial first line
+        (true, Some(newline)) => text[newline + 1..].to_string(),
+        _ => text.into_owned(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    const TRACE: &str = "0123456789abcdef";
+    const OTHER: &str = "fedcba9876543210";
+
+    #[test]
+    fn test_filters_lines_by_trace_id() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join(LOG_FILE_NAME);
+        let log = [
+            format!("t1 | INFO  | encrypt.rs:1 | Starting | trace={TRACE}"),
+            format!("t2 | INFO  | other.rs:1 | Unrelated | trace={OTHER}"),
+            "t3 | INFO  | lib.rs:1 | Application startup".to_string(),
+            format!("t4 | DEBUG | age.rs:9 | Writing | trace={TRACE} | spans: encrypt"),
+            format!("t5 | ERROR | encrypt.rs:2 | Failed | trace={TRACE} | {{code=1}}"),
+        ]
+        .join("\n");
+        std::fs::write(&path, log).unwrap();
+
+        let found = read_trace_lines_from(&path, TRACE, 10).unwrap();
+        assert_eq!(found.lines.len(), 3);
+        assert!(found.lines[0].starts_with("t1"));
+        assert!(!found.truncated);
+
+        // The latest lines are kept when there are too many
+        let found = read_trace_lines_from(&path, TRACE, 2).unwrap();
+        assert!(found.truncated);
+        assert!(found.lines[0].starts_with("t4"));
+        assert!(found.lines[1].starts_with("t5"));
+    }
+
+    #[test]
+    fn test_missing_log_and_bad_id() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join(LOG_FILE_NAME);
+
+        let found = read_trace_lines_from(&path, TRACE, 10).unwrap();
+        assert!(found.lines.is_empty());
+
+        let err = read_trace_lines_from(&path, "not-a-trace", 10).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    }
+}
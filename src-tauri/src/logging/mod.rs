@@ -4,7 +4,9 @@
 //! replacing the previous custom logging implementation.
 
 mod formatter;
+mod log_reader;
 pub mod redaction;
+pub mod trace_context;
 
 use crate::services::shared::infrastructure::path_management::PathProvider;
 use once_cell::sync::OnceCell;
@@ -19,6 +21,10 @@
 use tracing_subscriber::util::SubscriberInitExt;
 
 use crate::logging::formatter::BarqlyFormatter;
+use crate::logging::trace_context::TraceLayer;
+
+pub use log_reader::{TraceLogLines, read_trace_lines};
+pub use trace_context::{TraceId, current_trace_id, in_current_trace, spawn_blocking};
 
 static INIT: OnceCell<()> = OnceCell::new();
 
@@ -29,6 +35,9 @@
 pub const BUILD_TIMESTAMP: &str = env!("BUILD_TIMESTAMP");
 pub const RUSTC_VERSION: &str = env!("BUILD_RUSTC_VERSION");
 
+/// Log file the running app appends to, in the logs directory
+pub const LOG_FILE_NAME: &str = "barqly-vault.log";
+
 /// Get the platform-specific log directory using PathProvider
 /// This ensures consistency with the rest of the application
 fn get_log_dir() -> Result<PathBuf, io::Error> {
@@ -73,7 +82,7 @@ pub fn init() -> Result<(), Box<dyn std::error::Error>> {
     }
     INIT.get_or_try_init(|| -> Result<(), Box<dyn std::error::Error>> {
         let log_dir = get_log_dir()?;
-        let log_file_path = log_dir.join("barqly-vault.log");
+        let log_file_path = log_dir.join(LOG_FILE_NAME);
 
         // Create a file appender
         let file = fs::OpenOptions::new()
@@ -102,6 +111,7 @@ pub fn init() -> Result<(), Box<dyn std::error::Error>> {
                 // Default filter: info for our crate, warn for dependencies
                 EnvFilter::new("barqly_vault=info,warn")
             }))
+            .with(TraceLayer)
             .with(file_layer)
             .with(stderr_layer)
             .try_init()?;
@@ -3,58 +3,7 @@
 //! This module provides utilities to automatically redact sensitive information
 //! from logs to prevent accidental exposure of secrets.
 
-use serde::{Serialize, Serializer};
-use std::fmt;
-
-/// Wrapper type for sensitive strings that should be redacted in logs
-#[derive(Clone)]
-pub struct Sensitive<T>(pub T);
-
-impl<T> Sensitive<T> {
-    pub fn new(value: T) -> Self {
-        Sensitive(value)
-    }
-
-    /// Get the inner value (use with caution)
-    pub fn inner(&self) -> &T {
-        &self.0
-    }
-
-    /// Consume and return the inner value
-    pub fn into_inner(self) -> T {
-        self.0
-    }
-}
-
-impl<T> fmt::Debug for Sensitive<T> {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        #[cfg(debug_assertions)]
-        {
-            // In debug mode, show partial value for debugging
-            write!(f, "[SENSITIVE:***]")
-        }
-        #[cfg(not(debug_assertions))]
-        {
-            // In release mode, fully redact
-            write!(f, "[REDACTED]")
-        }
-    }
-}
-
-impl<T> fmt::Display for Sensitive<T> {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "[REDACTED]")
-    }
-}
-
-impl<T: Serialize> Serialize for Sensitive<T> {
-    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
-    where
-        S: Serializer,
-    {
-        serializer.serialize_str("[REDACTED]")
-    }
-}
+pub use crate::types::Sensitive;
 
 /// Macro to log sensitive data safely
 #[macro_export]
@@ -69,7 +18,7 @@ macro_rules! log_sensitive {
 
     // Redacted logging that shows in both dev and release
     (redacted: $field:expr) => {
-        $crate::tracing_setup::redaction::Sensitive::new($field)
+        $crate::types::Sensitive::new($field)
     };
 }
 
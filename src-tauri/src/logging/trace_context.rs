@@ -0,0 +1,245 @@
+//! Per-command correlation IDs
+//!
+//! Every Tauri command runs inside the span `#[instrument]` opens for it.
+//! `TraceLayer` gives each such span that isn't nested in another command a
+//! fresh `TraceId`, and every event under it - in nested service calls and
+//! across thread hops made with this module's `spawn_blocking` or
+//! `in_current_trace` - is written with that ID, so one failed command's log
+//! lines can be pulled out of the log with `get_logs_for_trace`.
+//!
+//! The same ID is copied into `CommandError` and `ProgressUpdate` via
+//! `current_trace_id`.
+
+use std::fmt;
+use tracing::span::{Attributes, Id};
+use tracing::{Dispatch, Span, Subscriber, dispatcher};
+use tracing_subscriber::Registry;
+use tracing_subscriber::layer::{Context, Layer};
+use tracing_subscriber::registry::{LookupSpan, SpanRef};
+
+/// Span target prefix of the command handlers
+pub const COMMANDS_TARGET: &str = concat!(env!("CARGO_CRATE_NAME"), "::commands");
+
+/// Hex digits in a trace ID
+const TRACE_ID_LEN: usize = 16;
+
+/// Correlation ID of one command invocation
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TraceId(String);
+
+impl TraceId {
+    pub fn generate() -> Self {
+        let mut id = uuid::Uuid::new_v4().simple().to_string();
+        id.truncate(TRACE_ID_LEN);
+        Self(id)
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// True if `value` has the shape of a generated ID
+    pub fn is_valid(value: &str) -> bool {
+        value.len() == TRACE_ID_LEN && value.bytes().all(|b| b.is_ascii_hexdigit())
+    }
+}
+
+impl fmt::Display for TraceId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// Assigns a `TraceId` to each outermost command span
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TraceLayer;
+
+impl<S> Layer<S> for TraceLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        if !attrs.metadata().target().starts_with(COMMANDS_TARGET) {
+            return;
+        }
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+        // A command called from another command shares the caller's ID
+        if span
+            .parent()
+            .is_some_and(|parent| trace_id_of(&parent).is_some())
+        {
+            return;
+        }
+        span.extensions_mut().insert(TraceId::generate());
+    }
+}
+
+/// The trace ID of `span` or its nearest ancestor that has one
+pub fn trace_id_of<'a, R: LookupSpan<'a>>(span: &SpanRef<'a, R>) -> Option<TraceId> {
+    span.scope()
+        .find_map(|span| span.extensions().get::<TraceId>().cloned())
+}
+
+/// The trace ID of the command the caller is running under, if any
+pub fn current_trace_id() -> Option<String> {
+    Span::current()
+        .with_subscriber(|(id, dispatch)| {
+            let registry = dispatch.downcast_ref::<Registry>()?;
+            trace_id_of(&registry.span(id)?)
+        })
+        .flatten()
+        .map(|trace_id| trace_id.0)
+}
+
+/// Wrap `f` to run under the caller's span and subscriber on another thread
+///
+/// Spans follow `.await`s on their own but not thread hops; use this for
+/// `std::thread::spawn` and anything else that takes a closure to run
+/// elsewhere.
+pub fn in_current_trace<F, R>(f: F) -> impl FnOnce() -> R + Send + 'static
+where
+    F: FnOnce() -> R + Send + 'static,
+{
+    let span = Span::current();
+    let dispatch = dispatcher::get_default(Dispatch::clone);
+    move || dispatcher::with_default(&dispatch, || span.in_scope(f))
+}
+
+/// `tokio::task::spawn_blocking` that keeps the caller's trace ID
+pub fn spawn_blocking<F, R>(f: F) -> tokio::task::JoinHandle<R>
+where
+    F: FnOnce() -> R + Send + 'static,
+    R: Send + 'static,
+{
+    tokio::task::spawn_blocking(in_current_trace(f))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+    use tracing::Instrument;
+    use tracing_subscriber::layer::SubscriberExt;
+
+    type Seen = Arc<Mutex<Vec<(String, Option<String>)>>>;
+
+    /// Records each event's message with the trace ID it was written under
+    struct Capture(Seen);
+
+    impl<S> Layer<S> for Capture
+    where
+        S: Subscriber + for<'a> LookupSpan<'a>,
+    {
+        fn on_event(&self, event: &tracing::Event<'_>, ctx: Context<'_, S>) {
+            struct Message(String);
+            impl tracing::field::Visit for Message {
+                fn record_debug(&mut self, field: &tracing::field::Field, value: &dyn fmt::Debug) {
+                    if field.name() == "message" {
+                        self.0 = format!("{value:?}");
+                    }
+                }
+            }
+
+            let mut message = Message(String::new());
+            event.record(&mut message);
+            let trace_id = ctx
+                .event_span(event)
+                .and_then(|span| trace_id_of(&span))
+                .map(|trace_id| trace_id.0);
+            self.0.lock().unwrap().push((message.0, trace_id));
+        }
+    }
+
+    fn subscriber(seen: &Seen) -> impl Subscriber + Send + Sync {
+        tracing_subscriber::registry()
+            .with(TraceLayer)
+            .with(Capture(seen.clone()))
+    }
+
+    async fn service_call(name: &'static str) -> Option<String> {
+        tracing::info!("{name}: async service");
+        tokio::task::yield_now().await;
+        spawn_blocking(move || {
+            tracing::info!("{name}: blocking pipeline");
+            let nested = std::thread::spawn(in_current_trace(move || {
+                tracing::info!("{name}: worker thread");
+            }));
+            nested.join().unwrap();
+        })
+        .await
+        .unwrap();
+        current_trace_id()
+    }
+
+    async fn command(name: &'static str) -> Option<String> {
+        async move {
+            tracing::info!("{name}: command");
+            let from_service = service_call(name).await;
+            assert_eq!(from_service, current_trace_id());
+            from_service
+        }
+        .instrument(tracing::info_span!(target: COMMANDS_TARGET, "command"))
+        .await
+    }
+
+    fn ids_for(seen: &Seen, name: &str) -> Vec<Option<String>> {
+        seen.lock()
+            .unwrap()
+            .iter()
+            .filter(|(message, _)| message.starts_with(name))
+            .map(|(_, trace_id)| trace_id.clone())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_nested_async_and_blocking_calls_share_the_id() {
+        let seen = Seen::default();
+        let _guard = tracing::subscriber::set_default(subscriber(&seen));
+
+        let trace_id = command("encrypt").await.expect("command has a trace ID");
+        assert!(TraceId::is_valid(&trace_id));
+
+        let ids = ids_for(&seen, "encrypt");
+        assert_eq!(ids.len(), 4);
+        assert!(
+            ids.iter()
+                .all(|id| id.as_deref() == Some(trace_id.as_str()))
+        );
+
+        // Outside a command there is no ID
+        assert_eq!(current_trace_id(), None);
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_commands_keep_their_own_ids() {
+        let seen = Seen::default();
+        let _guard = tracing::subscriber::set_default(subscriber(&seen));
+
+        let (first, second) = tokio::join!(command("first"), command("second"));
+        let (first, second) = (first.unwrap(), second.unwrap());
+        assert_ne!(first, second);
+
+        for (name, trace_id) in [("first", &first), ("second", &second)] {
+            let ids = ids_for(&seen, name);
+            assert_eq!(ids.len(), 4);
+            assert!(ids.iter().all(|id| id.as_ref() == Some(trace_id)));
+        }
+    }
+
+    #[test]
+    fn test_nested_command_span_inherits_the_id() {
+        let seen = Seen::default();
+        let _guard = tracing::subscriber::set_default(subscriber(&seen));
+
+        let outer = tracing::info_span!(target: COMMANDS_TARGET, "outer");
+        let _outer = outer.enter();
+        let outer_id = current_trace_id();
+        let inner = tracing::info_span!(target: COMMANDS_TARGET, "inner");
+        let _inner = inner.enter();
+
+        assert!(outer_id.is_some());
+        assert_eq!(current_trace_id(), outer_id);
+    }
+}
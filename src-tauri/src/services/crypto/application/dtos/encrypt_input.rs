@@ -1,7 +1,8 @@
 //! Encryption input DTO
 
+use crate::commands::validation::{NonEmptyId, NonEmptyList, input_rules, invalid};
 use crate::constants::MAX_FILES_PER_OPERATION;
-use crate::types::{CommandError, ErrorCode, ValidateInput, ValidationHelper};
+use crate::types::{CommandError, ValidationRule};
 use serde::Deserialize;
 
 /// Input for encryption command
@@ -11,37 +12,33 @@ pub struct EncryptDataInput {
     pub file_paths: Vec<String>,
     pub output_name: Option<String>,
     pub output_path: Option<String>,
+    /// Contacts to encrypt to as well as the key
+    #[serde(default)]
+    pub contact_ids: Vec<String>,
 }
 
-impl ValidateInput for EncryptDataInput {
-    fn validate(&self) -> Result<(), Box<CommandError>> {
-        ValidationHelper::validate_not_empty(&self.key_id, "Key ID")?;
-
-        if self.file_paths.is_empty() {
-            return Err(Box::new(
-                CommandError::operation(
-                    ErrorCode::MissingParameter,
-                    "At least one file must be selected",
-                )
-                .with_recovery_guidance("Please select one or more files to encrypt"),
-            ));
-        }
-
-        // Validate file count limit (from original validation)
-        if self.file_paths.len() > MAX_FILES_PER_OPERATION {
-            return Err(Box::new(
-                CommandError::operation(
-                    ErrorCode::TooManyFiles,
-                    format!(
-                        "Too many files selected: {} (maximum {})",
-                        self.file_paths.len(),
-                        MAX_FILES_PER_OPERATION
-                    ),
-                )
-                .with_recovery_guidance("Please select fewer files"),
-            ));
-        }
+fn check_file_count(input: &EncryptDataInput) -> Result<(), Box<CommandError>> {
+    if input.file_paths.len() > MAX_FILES_PER_OPERATION {
+        return Err(Box::new(
+            invalid(
+                "file_paths",
+                ValidationRule::InRange,
+                format!(
+                    "Too many files selected: {} (maximum {})",
+                    input.file_paths.len(),
+                    MAX_FILES_PER_OPERATION
+                ),
+            )
+            .with_recovery_guidance("Please select fewer files"),
+        ));
+    }
+    Ok(())
+}
 
-        Ok(())
+input_rules! {
+    EncryptDataInput {
+        key_id("Key ID"): [NonEmptyId],
+        file_paths("file"): [NonEmptyList],
     }
+    then check_file_count
 }
@@ -1,6 +1,18 @@
 //! Multi-key encryption input DTO
 
-use crate::types::{CommandError, ErrorCode, ValidateInput, ValidationHelper};
+use crate::commands::validation::{
+    ExistingDirectory, ExistingVaultId, NonEmptyList, PathWithinAllowedRoots, input_rules, invalid,
+};
+use crate::services::crypto::infrastructure::ArmoredOutputOptions;
+use crate::services::file::domain::models::{
+    MAX_REDUNDANCY_PERCENT, MIN_REDUNDANCY_PERCENT, ParityOptions,
+};
+use crate::services::file::infrastructure::file_operations::{
+    LockedFilePolicy, ResilientSourceConfig,
+};
+use crate::services::vault::domain::VaultError;
+use crate::services::vault::domain::models::validate_archive_comment;
+use crate::types::{CommandError, IoPriority, ValidationRule};
 use serde::Deserialize;
 
 #[derive(Debug, Deserialize, specta::Type)]
@@ -8,23 +20,84 @@ pub struct EncryptFilesMultiInput {
     pub vault_id: String,
     pub in_file_paths: Vec<String>,
     pub out_encrypted_file_name: Option<String>,
+    /// Folder to write the archive to instead of the vaults folder, such as
+    /// one on an external drive. The archive is named from the vault's
+    /// template, and a name another vault already has there is renamed or
+    /// refused as the app config's `cross_vault_name_policy` says.
     pub out_encrypted_file_path: Option<String>,
+    /// How to handle locked or unreadable source files (default: fail)
+    #[serde(default)]
+    pub locked_file_policy: Option<LockedFilePolicy>,
+    /// Read the source with retries, slow-media throttling and a
+    /// verify-after-copy pass (phones, cameras, SD cards). Off when omitted.
+    #[serde(default)]
+    pub resilient_source: Option<ResilientSourceConfig>,
+    /// Optional note about this encryption, searchable from the archive index.
+    /// Stored unencrypted, so it is length-capped and must not contain keys.
+    #[serde(default)]
+    pub comment: Option<String>,
+    /// Run the file I/O at background priority; defaults to the app setting
+    #[serde(default)]
+    pub io_priority: Option<IoPriority>,
+    /// Write an ASCII-armored archive whose first lines say, without a key,
+    /// what made it and where the recovery instructions are. Binary when omitted.
+    #[serde(default)]
+    pub armored_output: Option<ArmoredOutputOptions>,
+    /// Write Reed–Solomon parity beside the archive so blocks damaged on
+    /// storage media can be repaired later. None when omitted.
+    #[serde(default)]
+    pub generate_parity: Option<ParityOptions>,
+    /// Contacts to encrypt to as well, recorded apart from the vault keys.
+    /// They can't decrypt with the vault's keys or vice versa.
+    #[serde(default)]
+    pub contact_ids: Vec<String>,
+    /// Refuse a selection that contains the app's data, vault storage or the
+    /// output archive, instead of leaving those out (default: leave out)
+    #[serde(default)]
+    pub strict: Option<bool>,
 }
 
-impl ValidateInput for EncryptFilesMultiInput {
-    fn validate(&self) -> Result<(), Box<CommandError>> {
-        ValidationHelper::validate_not_empty(&self.vault_id, "Vault ID")?;
+fn check_comment(input: &EncryptFilesMultiInput) -> Result<(), Box<CommandError>> {
+    if let Err(e) = validate_archive_comment(input.comment.as_deref()) {
+        let message = match e {
+            VaultError::InvalidOperation(msg) => msg,
+            e => e.to_string(),
+        };
+        return Err(Box::new(invalid(
+            "comment",
+            ValidationRule::Format,
+            message,
+        )));
+    }
+    Ok(())
+}
+
+fn check_parity(input: &EncryptFilesMultiInput) -> Result<(), Box<CommandError>> {
+    if let Some(parity) = input.generate_parity
+        && !(MIN_REDUNDANCY_PERCENT..=MAX_REDUNDANCY_PERCENT).contains(&parity.redundancy_percent)
+    {
+        return Err(Box::new(invalid(
+            "generate_parity",
+            ValidationRule::InRange,
+            format!(
+                "Redundancy must be between {}% and {}%",
+                MIN_REDUNDANCY_PERCENT, MAX_REDUNDANCY_PERCENT
+            ),
+        )));
+    }
+    Ok(())
+}
 
-        if self.in_file_paths.is_empty() {
-            return Err(Box::new(
-                CommandError::operation(
-                    ErrorCode::MissingParameter,
-                    "At least one file must be selected",
-                )
-                .with_recovery_guidance("Please select one or more files to encrypt"),
-            ));
-        }
+fn check_options(input: &EncryptFilesMultiInput) -> Result<(), Box<CommandError>> {
+    check_comment(input)?;
+    check_parity(input)
+}
 
-        Ok(())
+input_rules! {
+    EncryptFilesMultiInput {
+        vault_id("Vault ID"): [ExistingVaultId],
+        in_file_paths("file"): [NonEmptyList],
+        out_encrypted_file_path("Output folder"): [PathWithinAllowedRoots, ExistingDirectory],
     }
+    then check_options
 }
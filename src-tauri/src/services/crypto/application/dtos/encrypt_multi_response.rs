@@ -1,5 +1,10 @@
 //! Multi-key encryption response DTO
 
+use crate::services::crypto::domain::models::TimingBreakdown;
+use crate::services::file::domain::models::{ParityInfo, UploadMetadata};
+use crate::services::file::infrastructure::file_operations::{
+    ResilientSourceReport, SelectionOverlap, SkippedEntry, TransformWarning,
+};
 use serde::Serialize;
 
 /// Response from multi-key encryption command
@@ -12,4 +17,34 @@ pub struct EncryptFilesMultiResponse {
     pub manifest_file_path: String,
     pub file_exists_warning: bool,
     pub keys_used: Vec<String>,
+    /// Checksums for uploading the backup bundle to S3-compatible storage
+    pub upload_metadata: Option<UploadMetadata>,
+    /// Source files left out because they were locked or unreadable
+    pub skipped_entries: Vec<SkippedEntry>,
+    /// False when any selected file was skipped
+    pub complete: bool,
+    /// Archive index entry for this encryption (for editing its comment)
+    pub archive_id: Option<String>,
+    /// Whether a previous backup bundle at the same path was replaced
+    pub replaced_existing: bool,
+    /// SHA-256 of the replaced backup bundle
+    pub previous_archive_sha256: Option<String>,
+    /// Read retries and verified files, when resilient-source mode was used
+    pub source_report: Option<ResilientSourceReport>,
+    /// Parity sidecar written beside the backup bundle, when requested
+    pub parity: Option<ParityInfo>,
+    /// App storage inside the selection that was left out of the archive
+    pub app_data_exclusions: Vec<SelectionOverlap>,
+    /// Files archived untransformed because their pre-encryption transform
+    /// failed
+    pub transform_warnings: Vec<TransformWarning>,
+    /// Time and bytes per pipeline phase
+    pub timing_breakdown: TimingBreakdown,
+    /// Removable volume the bundles were written to (USB drive, SD card)
+    pub volume_name: Option<String>,
+    /// The bundles were flushed to the drive and it can be unplugged; see
+    /// `prepare_eject` for files written afterwards
+    pub safe_to_eject: bool,
+    /// Why the drive isn't safe to eject, or that read-back was skipped
+    pub eject_warnings: Vec<String>,
 }
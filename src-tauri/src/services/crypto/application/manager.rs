@@ -3,21 +3,50 @@
 //! Facade for crypto operations following Command → Manager → Service pattern.
 //! Coordinates encryption, decryption, and progress tracking services.
 
-use super::services::{DecryptionOrchestrationService, EncryptionService};
+use super::services::{
+    ArchiveBrowseService, ArchiveMigrationService, ArchivePathRemapService, BatchArchiveTarget,
+    BatchDecryptionOptions, BatchDecryptionReport, BenchmarkService, BrowseSessionInfo,
+    CleanupSessionService, DecryptOptions, DecryptionOrchestrationService, EncryptionService,
+    KeyRecipientCheck, KeyRetrievalDecryptionService, ManifestResolution, PanicLockService,
+    RecoveryCredentials, RecoveryShareDecryptionService, RecoverySimulationReport,
+    RecoverySimulationService, RegeneratedManifest, SalvageReport, ToolIndependenceReport,
+    ToolIndependenceService, YubiKeyBatchDecryptionService,
+};
+use crate::prelude::*;
 use crate::services::crypto::application::dtos::{
     EncryptDataInput, EncryptFilesMultiInput, EncryptFilesMultiResponse,
 };
-use crate::services::crypto::domain::CryptoResult;
+use crate::services::crypto::domain::models::{
+    ArchiveMigrationReport, ArchiveRemapReport, BenchmarkProfile, BenchmarkResult, BenchmarkSizes,
+    CleanupResult, CleanupSessionSummary, EncryptionParameters, PanicLockPreview, PanicLockReport,
+    ParameterMigrationPlan, PathPrefixMapping,
+};
+use crate::services::crypto::domain::{CryptoError, CryptoResult};
+use crate::services::crypto::infrastructure::BenchmarkHistory;
+use crate::services::file::infrastructure::file_operations::{
+    EntryPreview, ExtractionResult, FileInfo, OwnershipPolicy, PathLimitStrategy, PreviewLimits,
+    RestoreFilter,
+};
+use crate::services::key_management::shared::KeyEntry;
+use crate::services::key_management::yubikey::application::services::{
+    DeviceService, YkmanDeviceService,
+};
+use crate::services::shared::infrastructure::io::OperationPriority;
 use crate::services::shared::infrastructure::progress::ProgressManager;
 use crate::services::vault::application::services::{
-    VaultBundleEncryptionInput, VaultBundleEncryptionService,
+    ArchiveService, ClipboardBackend, TransformSettingsService, VaultBundleEncryptionInput,
+    VaultBundleEncryptionService,
 };
-use std::path::PathBuf;
+use crate::services::vault::domain::VaultError;
+use crate::services::vault::domain::models::ArchiveIndexEntry;
+use std::path::{Path, PathBuf};
 
 pub struct CryptoManager {
     encryption_service: EncryptionService,
     decryption_orchestration: DecryptionOrchestrationService,
     vault_bundle_encryption: VaultBundleEncryptionService,
+    recovery_share_decryption: RecoveryShareDecryptionService,
+    yubikey_batch_decryption: YubiKeyBatchDecryptionService,
 }
 
 impl CryptoManager {
@@ -26,6 +55,8 @@ pub fn new() -> Self {
             encryption_service: EncryptionService::new(),
             decryption_orchestration: DecryptionOrchestrationService::new(),
             vault_bundle_encryption: VaultBundleEncryptionService::new(),
+            recovery_share_decryption: RecoveryShareDecryptionService::new(),
+            yubikey_batch_decryption: YubiKeyBatchDecryptionService::new(),
         }
     }
 
@@ -35,11 +66,14 @@ pub async fn encrypt_files(&self, input: EncryptDataInput) -> CryptoResult<Strin
     }
 
     /// Encrypt files with multiple keys (vault) - uses VaultBundleEncryptionService
+    ///
+    /// `io_priority` is read at every chunk boundary, so it can be switched
+    /// while the operation runs.
     pub async fn encrypt_files_multi(
         &self,
         input: EncryptFilesMultiInput,
+        io_priority: OperationPriority,
     ) -> CryptoResult<EncryptFilesMultiResponse> {
-        use crate::services::crypto::domain::CryptoError;
         use crate::services::vault;
 
         // Load vault to get name
@@ -51,21 +85,42 @@ pub async fn encrypt_files_multi(
         // Detect if single folder or multiple files
         let source_root = Self::detect_source_root(&input.in_file_paths);
 
-        // Check if output file would exist (before encryption)
-        let vaults_dir =
-            crate::services::shared::infrastructure::get_vaults_directory().map_err(|e| {
-                CryptoError::InvalidInput(format!("Failed to get vaults directory: {}", e))
-            })?;
-        let sanitized = crate::services::shared::infrastructure::sanitize_vault_name(vault.label())
-            .map_err(|e| CryptoError::InvalidInput(format!("Invalid vault name: {}", e)))?;
-        let encrypted_path = vaults_dir.join(format!("{}.age", sanitized.sanitized));
-        let file_exists_warning = encrypted_path.exists();
+        // Check if output file would exist (before encryption); in an output
+        // folder the name is only settled during encryption
+        let output_dir = input.out_encrypted_file_path.as_ref().map(PathBuf::from);
+        let file_exists_warning = if output_dir.is_none() {
+            let vaults_dir = crate::services::shared::infrastructure::get_vaults_directory()
+                .map_err(|e| {
+                    CryptoError::InvalidInput(format!("Failed to get vaults directory: {}", e))
+                })?;
+            let sanitized =
+                crate::services::shared::infrastructure::sanitize_vault_name(vault.label())
+                    .map_err(|e| CryptoError::InvalidInput(format!("Invalid vault name: {}", e)))?;
+            vaults_dir
+                .join(format!("{}.age", sanitized.sanitized))
+                .exists()
+        } else {
+            false
+        };
 
         let vault_input = VaultBundleEncryptionInput {
             vault_id: input.vault_id.clone(),
             vault_name: vault.label().to_string(),
             file_paths: input.in_file_paths.clone(),
             source_root,
+            locked_file_policy: input.locked_file_policy.unwrap_or_default(),
+            resilient_source: input.resilient_source.clone(),
+            comment: input.comment.clone(),
+            io_priority,
+            armored_output: input.armored_output.clone(),
+            generate_parity: input.generate_parity,
+            contact_ids: input.contact_ids.clone(),
+            strict: input.strict.unwrap_or(false),
+            parameters: super::services::vault_parameters(&input.vault_id),
+            migration: None,
+            output_dir,
+            archive_name: None,
+            transforms: TransformSettingsService::new().transforms_for_encryption(&input.vault_id),
         };
 
         // Use VaultBundleEncryptionService
@@ -73,8 +128,21 @@ pub async fn encrypt_files_multi(
             .vault_bundle_encryption
             .orchestrate_vault_encryption(vault_input)
             .await
-            .map_err(|e| {
-                CryptoError::EncryptionFailed(format!("Vault encryption failed: {}", e))
+            .map_err(|e| match e {
+                VaultError::ArchiveImmutable { archive_name, .. } => {
+                    CryptoError::ArchiveImmutable { archive_name }
+                }
+                VaultError::SelectionOverlapsAppData { paths } => {
+                    CryptoError::SelectionOverlapsAppData { paths }
+                }
+                VaultError::CrossVaultNameCollision {
+                    archive_name,
+                    other_vault,
+                } => CryptoError::CrossVaultNameCollision {
+                    archive_name,
+                    other_vault,
+                },
+                e => CryptoError::EncryptionFailed(format!("Vault encryption failed: {}", e)),
             })?;
 
         // Convert back to expected response
@@ -82,8 +150,22 @@ pub async fn encrypt_files_multi(
             encrypted_file_path: result.encrypted_file_path,
             shared_file_path: result.shared_file_path,
             manifest_file_path: result.manifest_path,
-            file_exists_warning,
+            file_exists_warning: file_exists_warning || result.replaced_existing,
             keys_used: result.keys_used,
+            upload_metadata: result.upload_metadata,
+            skipped_entries: result.skipped_entries,
+            complete: result.complete,
+            archive_id: result.archive_id,
+            replaced_existing: result.replaced_existing,
+            previous_archive_sha256: result.previous_archive_sha256,
+            source_report: result.source_report,
+            parity: result.parity,
+            app_data_exclusions: result.app_data_exclusions,
+            transform_warnings: result.transform_warnings,
+            timing_breakdown: result.timing_breakdown,
+            volume_name: result.volume_name,
+            safe_to_eject: result.safe_to_eject,
+            eject_warnings: result.eject_warnings,
         })
     }
 
@@ -106,6 +188,7 @@ fn detect_source_root(file_paths: &[String]) -> Option<String> {
     }
 
     /// Decrypt data using DecryptionOrchestrationService
+    #[allow(clippy::too_many_arguments)]
     pub async fn decrypt_data(
         &self,
         encrypted_file: &str,
@@ -113,6 +196,10 @@ pub async fn decrypt_data(
         passphrase: age::secrecy::SecretString,
         custom_output_dir: Option<PathBuf>, // Changed from &Path
         force_overwrite: bool,
+        path_limit_strategy: PathLimitStrategy,
+        ownership_policy: OwnershipPolicy,
+        restore_filter: RestoreFilter,
+        accept_last_attempt: bool,
         progress_manager: &mut ProgressManager,
     ) -> CryptoResult<super::services::DecryptionOutput> {
         let input = super::services::DecryptionInput {
@@ -121,12 +208,471 @@ pub async fn decrypt_data(
             passphrase,
             custom_output_dir, // Pass Option<PathBuf>
             force_overwrite,
+            path_limit_strategy,
+            ownership_policy,
+            restore_filter,
+            accept_last_attempt,
         };
 
         self.decryption_orchestration
             .decrypt(input, progress_manager)
             .await
     }
+
+    /// Check whether a key can open an archive, from its header alone
+    pub fn check_key_recipient(
+        &self,
+        encrypted_file: &str,
+        key_id: &str,
+    ) -> CryptoResult<KeyRecipientCheck> {
+        self.decryption_orchestration
+            .check_key_recipient(encrypted_file, key_id)
+    }
+
+    /// Which of an archive's recipients can open it right now
+    ///
+    /// Checks presence only: YubiKeys are listed, not opened, and nothing
+    /// asks for a PIN or passphrase.
+    pub async fn get_decrypt_options(&self, encrypted_file: &str) -> CryptoResult<DecryptOptions> {
+        let devices = YkmanDeviceService::new()
+            .await
+            .inspect_err(|e| debug!(error = %e, "YubiKey detection unavailable"))
+            .ok();
+        self.decryption_orchestration
+            .get_decrypt_options(
+                encrypted_file,
+                devices.as_ref().map(|d| d as &dyn DeviceService),
+            )
+            .await
+    }
+
+    /// `get_decrypt_options` for an archive in a vault's archive index
+    pub async fn get_archive_decrypt_options(
+        &self,
+        vault_id: &str,
+        archive_id: &str,
+    ) -> CryptoResult<DecryptOptions> {
+        let archives = ArchiveService::new()
+            .list_archives(vault_id)
+            .map_err(|e| CryptoError::InvalidInput(format!("Archive index unavailable: {}", e)))?;
+        let vaults_dir =
+            crate::services::shared::infrastructure::get_vaults_directory().map_err(|e| {
+                CryptoError::InvalidInput(format!("Failed to get vaults directory: {}", e))
+            })?;
+        let archive_path = browse_target(&archives, archive_id, &vaults_dir)?;
+        self.get_decrypt_options(&archive_path.to_string_lossy())
+            .await
+    }
+
+    /// Read the manifest embedded in an archive without extracting it
+    pub fn read_archive_manifest(
+        &self,
+        encrypted_file: &str,
+        key_id: &str,
+        passphrase: age::secrecy::SecretString,
+    ) -> CryptoResult<ManifestResolution> {
+        self.decryption_orchestration
+            .read_archive_manifest(encrypted_file, key_id, passphrase)
+    }
+
+    /// Read an archive's embedded manifest and preview its files in memory
+    pub fn read_archive_previews(
+        &self,
+        encrypted_file: &str,
+        key_id: &str,
+        passphrase: age::secrecy::SecretString,
+        limits: &PreviewLimits,
+    ) -> CryptoResult<(ManifestResolution, Vec<EntryPreview>)> {
+        self.decryption_orchestration.read_archive_previews(
+            encrypted_file,
+            key_id,
+            passphrase,
+            limits,
+        )
+    }
+
+    /// Rewrite a vault's external manifest from the copy embedded in its archive
+    pub fn regenerate_external_manifest(
+        &self,
+        encrypted_file: &str,
+        key_id: &str,
+        passphrase: age::secrecy::SecretString,
+    ) -> CryptoResult<RegeneratedManifest> {
+        self.decryption_orchestration.regenerate_external_manifest(
+            encrypted_file,
+            key_id,
+            passphrase,
+        )
+    }
+
+    /// Recover what survives of a damaged archive, or with no `output_dir`
+    /// only predict what would be recovered
+    pub fn salvage_archive(
+        &self,
+        encrypted_file: &str,
+        key_id: &str,
+        passphrase: age::secrecy::SecretString,
+        output_dir: Option<&Path>,
+    ) -> CryptoResult<SalvageReport> {
+        self.decryption_orchestration.salvage_archive(
+            encrypted_file,
+            key_id,
+            passphrase,
+            output_dir,
+        )
+    }
+
+    /// Check an archive recovers with standard age and tar
+    ///
+    /// When it does, the recovery transcript is written into the recovery
+    /// kit beside the archive; failing to write it only warns.
+    pub fn verify_tool_independence(
+        &self,
+        vault_id: &str,
+        archive_id: &str,
+        key_id: &str,
+        passphrase: age::secrecy::SecretString,
+    ) -> CryptoResult<ToolIndependenceReport> {
+        let archives = ArchiveService::new()
+            .list_archives(vault_id)
+            .map_err(|e| CryptoError::InvalidInput(format!("Archive index unavailable: {}", e)))?;
+        let vaults_dir =
+            crate::services::shared::infrastructure::get_vaults_directory().map_err(|e| {
+                CryptoError::InvalidInput(format!("Failed to get vaults directory: {}", e))
+            })?;
+        let archive_path = browse_target(&archives, archive_id, &vaults_dir)?;
+
+        let mut report = self.decryption_orchestration.verify_tool_independence(
+            &archive_path,
+            key_id,
+            passphrase,
+        )?;
+
+        if report.is_independent() {
+            let kit_path = recovery_kit_path(&archive_path);
+            match ToolIndependenceService::new()
+                .append_to_recovery_kit(&kit_path, &report.transcript)
+            {
+                Ok(()) => report.recovery_kit = Some(kit_path.to_string_lossy().to_string()),
+                Err(e) => warn!(error = %e, "Failed to add transcript to recovery kit"),
+            }
+        }
+        Ok(report)
+    }
+
+    /// Rehearse restoring an archive from a recovery kit in a sandbox
+    ///
+    /// Nothing outside the sandbox is written, and the live profile is only
+    /// checked for what the kit lacks.
+    pub fn simulate_recovery(
+        &self,
+        kit_path: &Path,
+        archive_path: &Path,
+        credentials: Option<RecoveryCredentials>,
+    ) -> CryptoResult<RecoverySimulationReport> {
+        RecoverySimulationService::new().simulate(kit_path, archive_path, credentials.as_ref())
+    }
+
+    /// Decrypt a vault using Shamir recovery shares
+    pub fn decrypt_with_recovery_shares(
+        &self,
+        vault_id: &str,
+        shares: &[String],
+        encrypted_file: &Path,
+        output_dir: &Path,
+    ) -> CryptoResult<ExtractionResult> {
+        self.recovery_share_decryption
+            .decrypt(vault_id, shares, encrypted_file, output_dir)
+    }
+
+    /// Decrypt several archives of a vault with one YubiKey unlock
+    ///
+    /// Each archive is extracted to its own `<name>-r<revision>` folder under
+    /// `output_dir` so revisions of the same vault don't overwrite each other.
+    #[allow(clippy::too_many_arguments)]
+    pub fn decrypt_batch(
+        &self,
+        operation_id: &str,
+        vault_id: &str,
+        archive_ids: &[String],
+        output_dir: &Path,
+        key_id: &str,
+        pin: age::secrecy::SecretString,
+        options: BatchDecryptionOptions,
+    ) -> CryptoResult<BatchDecryptionReport> {
+        let key_entry = KeyRetrievalDecryptionService::new().get_decryption_key_info(key_id)?;
+        if !matches!(key_entry, KeyEntry::Yubikey { .. }) {
+            return Err(CryptoError::InvalidInput(
+                "Batch decryption requires a YubiKey key".to_string(),
+            ));
+        }
+
+        let archives = ArchiveService::new()
+            .list_archives(vault_id)
+            .map_err(|e| CryptoError::InvalidInput(format!("Archive index unavailable: {}", e)))?;
+        let vaults_dir =
+            crate::services::shared::infrastructure::get_vaults_directory().map_err(|e| {
+                CryptoError::InvalidInput(format!("Failed to get vaults directory: {}", e))
+            })?;
+        let targets = batch_targets(&archives, archive_ids, &vaults_dir, output_dir)?;
+
+        self.yubikey_batch_decryption.decrypt_batch(
+            operation_id,
+            &key_entry,
+            &pin,
+            &targets,
+            options,
+        )
+    }
+
+    /// Decrypt an archive into memory and serve it read-only on localhost
+    pub fn browse_archive(
+        &self,
+        vault_id: &str,
+        archive_id: &str,
+        key_id: &str,
+        passphrase: age::secrecy::SecretString,
+    ) -> CryptoResult<BrowseSessionInfo> {
+        let archives = ArchiveService::new()
+            .list_archives(vault_id)
+            .map_err(|e| CryptoError::InvalidInput(format!("Archive index unavailable: {}", e)))?;
+        let vaults_dir =
+            crate::services::shared::infrastructure::get_vaults_directory().map_err(|e| {
+                CryptoError::InvalidInput(format!("Failed to get vaults directory: {}", e))
+            })?;
+        let encrypted_file = browse_target(&archives, archive_id, &vaults_dir)?;
+
+        ArchiveBrowseService::new().browse_archive(
+            vault_id,
+            archive_id,
+            &encrypted_file,
+            key_id,
+            passphrase,
+        )
+    }
+
+    /// Which of a vault's archives don't match the target parameters
+    pub fn plan_parameter_migration(
+        &self,
+        vault_id: &str,
+        target: &EncryptionParameters,
+    ) -> CryptoResult<ParameterMigrationPlan> {
+        ArchiveMigrationService::new().plan(vault_id, target)
+    }
+
+    /// Re-encrypt a vault's current archive under the target parameters
+    pub async fn migrate_archive(
+        &self,
+        vault_id: &str,
+        archive_id: &str,
+        target: &EncryptionParameters,
+        key_id: &str,
+        passphrase: age::secrecy::SecretString,
+        prune_original: bool,
+    ) -> CryptoResult<ArchiveMigrationReport> {
+        let (entry, archive_path) = current_archive(vault_id, archive_id)?;
+
+        ArchiveMigrationService::new()
+            .migrate(
+                &self.decryption_orchestration,
+                &entry,
+                &archive_path,
+                target,
+                key_id,
+                passphrase,
+                prune_original,
+            )
+            .await
+    }
+
+    /// Re-encrypt a vault's current archive with its internal paths remapped
+    pub async fn remap_archive_paths(
+        &self,
+        vault_id: &str,
+        archive_id: &str,
+        mappings: &[PathPrefixMapping],
+        key_id: &str,
+        passphrase: age::secrecy::SecretString,
+    ) -> CryptoResult<ArchiveRemapReport> {
+        let (entry, archive_path) = current_archive(vault_id, archive_id)?;
+
+        ArchivePathRemapService::new()
+            .remap(
+                &self.decryption_orchestration,
+                &entry,
+                &archive_path,
+                mappings,
+                key_id,
+                passphrase,
+            )
+            .await
+    }
+
+    /// Close a browse session and wipe its snapshot; false if already closed
+    pub fn stop_browsing(&self, session_id: &str) -> bool {
+        ArchiveBrowseService::new().stop_browsing(session_id)
+    }
+
+    /// Benchmark this machine on synthetic data and record the result
+    pub fn run_benchmark(
+        &self,
+        profile: BenchmarkProfile,
+        sizes: BenchmarkSizes,
+    ) -> CryptoResult<BenchmarkResult> {
+        BenchmarkService::new().run(profile, sizes)
+    }
+
+    /// Recorded benchmark results, oldest first
+    pub fn benchmark_history(&self) -> CryptoResult<Vec<BenchmarkResult>> {
+        BenchmarkHistory::load()
+            .map(|history| history.results)
+            .map_err(|e| CryptoError::IoError(format!("Failed to load benchmark history: {e}")))
+    }
+
+    /// Schedule extracted files for secure deletion after `ttl_minutes`
+    pub fn register_cleanup_session(
+        &self,
+        output_dir: &Path,
+        files: &[FileInfo],
+        ttl_minutes: u32,
+    ) -> CryptoResult<CleanupSessionSummary> {
+        CleanupSessionService::new().register(output_dir, files, ttl_minutes)
+    }
+
+    /// Pending cleanups of decrypted output, soonest first
+    pub fn list_cleanup_sessions(&self) -> CryptoResult<Vec<CleanupSessionSummary>> {
+        CleanupSessionService::new().list()
+    }
+
+    pub fn extend_cleanup_session(
+        &self,
+        session_id: &str,
+        minutes: u32,
+    ) -> CryptoResult<CleanupSessionSummary> {
+        CleanupSessionService::new().extend(session_id, minutes)
+    }
+
+    pub fn cancel_cleanup_session(&self, session_id: &str) -> CryptoResult<()> {
+        CleanupSessionService::new().cancel(session_id)
+    }
+
+    /// Delete (or refuse to delete) the output of every overdue session
+    pub fn run_due_cleanups(&self) -> CryptoResult<Vec<CleanupResult>> {
+        CleanupSessionService::new().run_due()
+    }
+
+    /// Delete the output of one pending session now
+    pub fn run_cleanup_session_now(&self, session_id: &str) -> CryptoResult<CleanupResult> {
+        CleanupSessionService::new().run_now(session_id)
+    }
+
+    /// What a panic lock would clear right now
+    pub fn preview_panic_lock(&self) -> CryptoResult<PanicLockPreview> {
+        PanicLockService::new().preview()
+    }
+
+    /// Wipe cached sensitive state once the typed confirmation matches
+    pub fn panic_lock(
+        &self,
+        confirmation: Option<&str>,
+        clipboard: Option<&dyn ClipboardBackend>,
+    ) -> CryptoResult<PanicLockReport> {
+        PanicLockService::new().lock(confirmation, clipboard)
+    }
+}
+
+/// Recovery kit written beside an archive (`Vault.age` → `Vault-RECOVERY.txt`)
+fn recovery_kit_path(archive_path: &Path) -> PathBuf {
+    let name = archive_path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let stem = name.strip_suffix(".age").unwrap_or(&name);
+    archive_path.with_file_name(format!("{stem}-RECOVERY.txt"))
+}
+
+/// Resolve a vault's archive ID to its entry and encrypted file
+///
+/// Only the newest revision of each archive name is kept on disk, so an
+/// archive superseded by a later encryption can no longer be opened.
+fn current_archive(vault_id: &str, archive_id: &str) -> CryptoResult<(ArchiveIndexEntry, PathBuf)> {
+    let archives = ArchiveService::new()
+        .list_archives(vault_id)
+        .map_err(|e| CryptoError::InvalidInput(format!("Archive index unavailable: {}", e)))?;
+    let vaults_dir = crate::services::shared::infrastructure::get_vaults_directory()
+        .map_err(|e| CryptoError::InvalidInput(format!("Failed to get vaults directory: {}", e)))?;
+    let archive_path = browse_target(&archives, archive_id, &vaults_dir)?;
+    let entry = archives
+        .into_iter()
+        .find(|a| a.archive_id == archive_id)
+        .ok_or_else(|| CryptoError::InvalidInput(format!("Archive '{}' not found", archive_id)))?;
+    Ok((entry, archive_path))
+}
+
+fn browse_target(
+    archives: &[ArchiveIndexEntry],
+    archive_id: &str,
+    vaults_dir: &Path,
+) -> CryptoResult<PathBuf> {
+    let entry = archives
+        .iter()
+        .find(|a| a.archive_id == archive_id)
+        .ok_or_else(|| CryptoError::InvalidInput(format!("Archive '{}' not found", archive_id)))?;
+
+    if archives.iter().any(|a| {
+        a.archive_name == entry.archive_name && a.encryption_revision > entry.encryption_revision
+    }) {
+        return Err(CryptoError::FileNotFound(format!(
+            "{} revision {} (replaced by a newer encryption)",
+            entry.archive_name, entry.encryption_revision
+        )));
+    }
+
+    Ok(vaults_dir.join(&entry.archive_name))
+}
+
+/// Resolve requested archive IDs to files and output folders, keeping request order
+fn batch_targets(
+    archives: &[ArchiveIndexEntry],
+    archive_ids: &[String],
+    vaults_dir: &Path,
+    output_dir: &Path,
+) -> CryptoResult<Vec<BatchArchiveTarget>> {
+    if archive_ids.is_empty() {
+        return Err(CryptoError::InvalidInput(
+            "No archives selected for batch decryption".to_string(),
+        ));
+    }
+
+    let mut targets: Vec<BatchArchiveTarget> = Vec::with_capacity(archive_ids.len());
+    for archive_id in archive_ids {
+        if targets.iter().any(|t| &t.archive_id == archive_id) {
+            return Err(CryptoError::InvalidInput(format!(
+                "Archive '{}' is listed more than once",
+                archive_id
+            )));
+        }
+
+        let entry = archives
+            .iter()
+            .find(|a| &a.archive_id == archive_id)
+            .ok_or_else(|| {
+                CryptoError::InvalidInput(format!("Archive '{}' not found", archive_id))
+            })?;
+        let stem = Path::new(&entry.archive_name)
+            .file_stem()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_else(|| entry.archive_id.clone());
+
+        targets.push(BatchArchiveTarget {
+            archive_id: entry.archive_id.clone(),
+            archive_name: entry.archive_name.clone(),
+            encrypted_path: vaults_dir.join(&entry.archive_name),
+            output_dir: output_dir.join(format!("{}-r{}", stem, entry.encryption_revision)),
+        });
+    }
+
+    Ok(targets)
 }
 
 impl Default for CryptoManager {
@@ -144,4 +690,79 @@ fn test_crypto_manager_creation() {
         let _manager = CryptoManager::new();
         // Verify creation works
     }
+
+    fn archive(id: &str, revision: u32) -> ArchiveIndexEntry {
+        ArchiveIndexEntry {
+            archive_id: id.to_string(),
+            vault_id: "vault-001".to_string(),
+            archive_name: "Family-Documents.age".to_string(),
+            encryption_revision: revision,
+            created_at: chrono::Utc::now(),
+            file_count: 3,
+            comment: None,
+            comment_updated_at: None,
+            immutable: false,
+            parity: None,
+            migrated_from: None,
+            prune_marked: false,
+            remapped_from: None,
+            archive_sha256: None,
+            recipients: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_batch_targets_resolve_in_request_order() {
+        let archives = [archive("a1", 1), archive("a2", 2)];
+        let ids = ["a2".to_string(), "a1".to_string()];
+
+        let targets =
+            batch_targets(&archives, &ids, Path::new("/vaults"), Path::new("/out")).unwrap();
+
+        assert_eq!(targets[0].archive_id, "a2");
+        assert_eq!(
+            targets[0].encrypted_path,
+            Path::new("/vaults/Family-Documents.age")
+        );
+        assert_eq!(targets[0].output_dir, Path::new("/out/Family-Documents-r2"));
+        assert_eq!(targets[1].output_dir, Path::new("/out/Family-Documents-r1"));
+    }
+
+    #[test]
+    fn test_batch_targets_reject_bad_selections() {
+        let archives = [archive("a1", 1)];
+        let resolve = |ids: &[&str]| {
+            let ids: Vec<String> = ids.iter().map(|id| id.to_string()).collect();
+            batch_targets(&archives, &ids, Path::new("/vaults"), Path::new("/out"))
+        };
+
+        assert!(matches!(resolve(&[]), Err(CryptoError::InvalidInput(_))));
+        assert!(matches!(
+            resolve(&["a1", "a1"]),
+            Err(CryptoError::InvalidInput(_))
+        ));
+        assert!(matches!(
+            resolve(&["missing"]),
+            Err(CryptoError::InvalidInput(_))
+        ));
+    }
+
+    #[test]
+    fn test_browse_target_only_opens_latest_revision() {
+        let archives = [archive("a1", 1), archive("a2", 2)];
+        let vaults = Path::new("/vaults");
+
+        assert_eq!(
+            browse_target(&archives, "a2", vaults).unwrap(),
+            Path::new("/vaults/Family-Documents.age")
+        );
+        assert!(matches!(
+            browse_target(&archives, "a1", vaults),
+            Err(CryptoError::FileNotFound(_))
+        ));
+        assert!(matches!(
+            browse_target(&archives, "missing", vaults),
+            Err(CryptoError::InvalidInput(_))
+        ));
+    }
 }
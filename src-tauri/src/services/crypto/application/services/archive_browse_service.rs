@@ -0,0 +1,369 @@
+//! Archive Browse Service
+//!
+//! Opens a decrypted archive for read-only browsing without extracting it.
+//! The archive is decrypted into memory (spilling to a secure temporary file
+//! past the memory budget) and served on 127.0.0.1 behind a random token
+//! path. Only files listed in the archive's manifest are exposed.
+//!
+//! Sessions end when stopped, after `BROWSE_IDLE_TIMEOUT` without a request,
+//! or through `stop_all` when the app locks. Ending a session closes the
+//! server and zeroizes the snapshot.
+
+use super::DecryptionOrchestrationService;
+use crate::prelude::*;
+use crate::services::crypto::domain::{CryptoError, CryptoResult};
+use crate::services::crypto::infrastructure::{BrowseServer, SnapshotLimits, SnapshotStore};
+use crate::types::{ByteSize, DurationMs};
+use age::secrecy::SecretString;
+use rand::RngCore;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::sync::{Arc, Mutex, MutexGuard, OnceLock};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Browse sessions with no request for this long are closed
+pub const BROWSE_IDLE_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+/// How often idle sessions are checked
+pub const BROWSE_SWEEP_INTERVAL: Duration = Duration::from_secs(5);
+
+/// An open browse session
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct BrowseSessionInfo {
+    pub session_id: String,
+    pub vault_id: String,
+    pub archive_id: String,
+    /// Local URL including the access token; open it in the system browser
+    pub url: String,
+    pub file_count: usize,
+    pub memory_bytes: ByteSize,
+    /// Bytes spilled to a secure temporary file (0 when held in memory)
+    pub spilled_bytes: ByteSize,
+    pub idle_timeout_ms: DurationMs,
+}
+
+struct BrowseSession {
+    info: BrowseSessionInfo,
+    server: BrowseServer,
+    store: Arc<SnapshotStore>,
+}
+
+impl BrowseSession {
+    fn teardown(mut self) {
+        self.server.stop();
+        self.store.wipe();
+        info!(
+            session_id = %self.info.session_id,
+            vault_id = %self.info.vault_id,
+            "Archive browse session closed"
+        );
+    }
+}
+
+/// Open browse sessions, at most one per vault
+pub struct BrowseSessionRegistry {
+    sessions: Mutex<HashMap<String, BrowseSession>>,
+    idle_timeout: Duration,
+}
+
+impl BrowseSessionRegistry {
+    pub fn new(idle_timeout: Duration) -> Self {
+        Self {
+            sessions: Mutex::new(HashMap::new()),
+            idle_timeout,
+        }
+    }
+
+    /// Process-wide registry; starts the idle sweeper on first use
+    pub fn global() -> &'static BrowseSessionRegistry {
+        static REGISTRY: OnceLock<BrowseSessionRegistry> = OnceLock::new();
+        static SWEEPER: OnceLock<()> = OnceLock::new();
+
+        let registry = REGISTRY.get_or_init(|| BrowseSessionRegistry::new(BROWSE_IDLE_TIMEOUT));
+        SWEEPER.get_or_init(|| {
+            thread::spawn(|| {
+                loop {
+                    thread::sleep(BROWSE_SWEEP_INTERVAL);
+                    BrowseSessionRegistry::global().sweep(Instant::now());
+                }
+            });
+        });
+        registry
+    }
+
+    /// Fail if the vault already has an open session
+    pub fn ensure_vacant(&self, vault_id: &str) -> CryptoResult<()> {
+        if self
+            .lock_sessions()
+            .values()
+            .any(|s| s.info.vault_id == vault_id)
+        {
+            return Err(CryptoError::OperationInProgress);
+        }
+        Ok(())
+    }
+
+    /// Serve a snapshot and register the session
+    pub fn open(
+        &self,
+        vault_id: &str,
+        archive_id: &str,
+        store: SnapshotStore,
+    ) -> CryptoResult<BrowseSessionInfo> {
+        let mut sessions = self.lock_sessions();
+        if sessions.values().any(|s| s.info.vault_id == vault_id) {
+            return Err(CryptoError::OperationInProgress);
+        }
+
+        let mut token = [0u8; 32];
+        rand::thread_rng().fill_bytes(&mut token);
+        let store = Arc::new(store);
+        let server = BrowseServer::start(Arc::clone(&store), hex::encode(token))
+            .map_err(|e| CryptoError::IoError(format!("Failed to start browse server: {e}")))?;
+
+        let info = BrowseSessionInfo {
+            session_id: uuid::Uuid::new_v4().to_string(),
+            vault_id: vault_id.to_string(),
+            archive_id: archive_id.to_string(),
+            url: server.url(),
+            file_count: store.file_count(),
+            memory_bytes: ByteSize(store.memory_bytes()),
+            spilled_bytes: ByteSize(store.spilled_bytes()),
+            idle_timeout_ms: self.idle_timeout.into(),
+        };
+        info!(
+            session_id = %info.session_id,
+            vault_id = %vault_id,
+            archive_id = %archive_id,
+            files = info.file_count,
+            "Archive browse session opened"
+        );
+
+        sessions.insert(
+            info.session_id.clone(),
+            BrowseSession {
+                info: info.clone(),
+                server,
+                store,
+            },
+        );
+        Ok(info)
+    }
+
+    /// Close a session; false if it was already gone
+    pub fn stop(&self, session_id: &str) -> bool {
+        let session = self.lock_sessions().remove(session_id);
+        match session {
+            Some(session) => {
+                session.teardown();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Close every session; returns how many were open
+    ///
+    /// Call when the app locks so no decrypted snapshot outlives it.
+    pub fn stop_all(&self) -> usize {
+        let sessions: Vec<BrowseSession> = self.lock_sessions().drain().map(|(_, s)| s).collect();
+        let count = sessions.len();
+        for session in sessions {
+            session.teardown();
+        }
+        count
+    }
+
+    /// Close sessions idle past the timeout; returns their IDs
+    pub fn sweep(&self, now: Instant) -> Vec<String> {
+        let expired: Vec<BrowseSession> = {
+            let mut sessions = self.lock_sessions();
+            let ids: Vec<String> = sessions
+                .iter()
+                .filter(|(_, s)| s.server.idle_for(now) >= self.idle_timeout)
+                .map(|(id, _)| id.clone())
+                .collect();
+            ids.iter().filter_map(|id| sessions.remove(id)).collect()
+        };
+
+        expired
+            .into_iter()
+            .map(|session| {
+                let id = session.info.session_id.clone();
+                info!(session_id = %id, "Archive browse session idle, closing");
+                session.teardown();
+                id
+            })
+            .collect()
+    }
+
+    /// Open sessions
+    pub fn list(&self) -> Vec<BrowseSessionInfo> {
+        self.lock_sessions()
+            .values()
+            .map(|s| s.info.clone())
+            .collect()
+    }
+
+    fn lock_sessions(&self) -> MutexGuard<'_, HashMap<String, BrowseSession>> {
+        self.sessions.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Service for opening archives as browse sessions
+pub struct ArchiveBrowseService {
+    decryption: DecryptionOrchestrationService,
+    registry: &'static BrowseSessionRegistry,
+    limits: SnapshotLimits,
+}
+
+impl ArchiveBrowseService {
+    pub fn new() -> Self {
+        Self {
+            decryption: DecryptionOrchestrationService::new(),
+            registry: BrowseSessionRegistry::global(),
+            limits: SnapshotLimits::default(),
+        }
+    }
+
+    /// Decrypt an archive and serve it read-only on localhost
+    #[instrument(skip(self, passphrase))]
+    pub fn browse_archive(
+        &self,
+        vault_id: &str,
+        archive_id: &str,
+        encrypted_file: &Path,
+        key_id: &str,
+        passphrase: SecretString,
+    ) -> CryptoResult<BrowseSessionInfo> {
+        // Checked again on registration; this just avoids a wasted decryption
+        self.registry.ensure_vacant(vault_id)?;
+
+        let (plaintext, resolution) = self.decryption.decrypt_in_memory(
+            &encrypted_file.to_string_lossy(),
+            key_id,
+            passphrase,
+        )?;
+        let manifest = resolution.preferred().ok_or_else(|| {
+            CryptoError::InvalidInput(
+                "Archive has no manifest, so its contents can't be listed".to_string(),
+            )
+        })?;
+        let known: HashSet<&str> = manifest
+            .content
+            .files
+            .iter()
+            .map(|f| f.path.as_str())
+            .collect();
+
+        let store = SnapshotStore::from_archive(
+            &plaintext,
+            |path| is_manifest_path(&known, path),
+            self.limits,
+        )
+        .map_err(|e| match e.kind() {
+            std::io::ErrorKind::FileTooLarge => CryptoError::FileTooLarge(e.to_string()),
+            _ => CryptoError::InvalidInput(format!("Failed to read archive: {e}")),
+        })?;
+        drop(plaintext);
+
+        self.registry.open(vault_id, archive_id, store)
+    }
+
+    pub fn stop_browsing(&self, session_id: &str) -> bool {
+        self.registry.stop(session_id)
+    }
+
+    pub fn stop_all(&self) -> usize {
+        self.registry.stop_all()
+    }
+}
+
+impl Default for ArchiveBrowseService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Whether a tar entry is a file the manifest knows about
+///
+/// Folder archives store entries under the staged folder name while the
+/// manifest records paths relative to it.
+fn is_manifest_path(known: &HashSet<&str>, tar_path: &str) -> bool {
+    known.contains(tar_path)
+        || tar_path
+            .split_once('/')
+            .is_some_and(|(_, rest)| known.contains(rest))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::crypto::infrastructure::archive_browse::test_archive;
+
+    fn store() -> SnapshotStore {
+        let archive = test_archive(&[("docs/will.pdf", b"0123456789")]);
+        SnapshotStore::from_archive(&archive, |_| true, SnapshotLimits::default()).unwrap()
+    }
+
+    #[test]
+    fn test_one_session_per_vault() {
+        let registry = BrowseSessionRegistry::new(BROWSE_IDLE_TIMEOUT);
+        let first = registry.open("vault-1", "archive-1", store()).unwrap();
+
+        assert!(matches!(
+            registry.ensure_vacant("vault-1"),
+            Err(CryptoError::OperationInProgress)
+        ));
+        assert!(matches!(
+            registry.open("vault-1", "archive-2", store()),
+            Err(CryptoError::OperationInProgress)
+        ));
+        let other = registry.open("vault-2", "archive-3", store()).unwrap();
+
+        assert_ne!(first.url, other.url);
+        assert_eq!(first.file_count, 1);
+        assert_eq!(registry.list().len(), 2);
+        assert_eq!(registry.stop_all(), 2);
+    }
+
+    #[test]
+    fn test_stop_closes_server_and_wipes_snapshot() {
+        let registry = BrowseSessionRegistry::new(BROWSE_IDLE_TIMEOUT);
+        let info = registry.open("vault-1", "archive-1", store()).unwrap();
+        let (addr, store) = {
+            let sessions = registry.lock_sessions();
+            let session = &sessions[&info.session_id];
+            (session.server.local_addr(), Arc::clone(&session.store))
+        };
+
+        assert!(registry.stop(&info.session_id));
+
+        assert!(store.is_wiped());
+        assert_eq!(store.memory_bytes(), 0);
+        assert!(std::net::TcpStream::connect(addr).is_err());
+        assert!(!registry.stop(&info.session_id));
+        assert!(registry.ensure_vacant("vault-1").is_ok());
+    }
+
+    #[test]
+    fn test_sweep_closes_idle_sessions() {
+        let registry = BrowseSessionRegistry::new(Duration::from_secs(60));
+        let info = registry.open("vault-1", "archive-1", store()).unwrap();
+
+        assert!(registry.sweep(Instant::now()).is_empty());
+        let later = Instant::now() + Duration::from_secs(61);
+        assert_eq!(registry.sweep(later), vec![info.session_id]);
+        assert!(registry.list().is_empty());
+    }
+
+    #[test]
+    fn test_only_manifest_paths_are_visible() {
+        let known: HashSet<&str> = ["will.pdf", "photos/a.jpg"].into_iter().collect();
+
+        assert!(is_manifest_path(&known, "will.pdf"));
+        assert!(is_manifest_path(&known, "Family/photos/a.jpg"));
+        assert!(!is_manifest_path(&known, "Family.manifest"));
+        assert!(!is_manifest_path(&known, "Family/notes.txt"));
+    }
+}
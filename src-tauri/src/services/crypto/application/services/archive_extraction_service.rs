@@ -5,7 +5,10 @@
 use crate::prelude::*;
 use crate::services::crypto::domain::{CryptoError, CryptoResult};
 use crate::services::file::infrastructure::file_operations;
+use crate::services::shared::infrastructure::SecureDeleteService;
+use crate::services::shared::infrastructure::io::IoPacer;
 use std::path::Path;
+use std::sync::Arc;
 
 /// Service for archive extraction operations
 #[derive(Debug)]
@@ -17,12 +20,44 @@ pub fn new() -> Self {
     }
 
     /// Extract decrypted archive to output directory
-    #[instrument(skip(self, decrypted_data))]
+    ///
+    /// Entries exceeding platform path limits are handled per `path_limit_strategy`;
+    /// any renamed entries are reported in the result.
     pub fn extract_archive(
         &self,
         decrypted_data: &[u8],
         output_path: &Path,
-    ) -> CryptoResult<Vec<file_operations::FileInfo>> {
+        path_limit_strategy: file_operations::PathLimitStrategy,
+    ) -> CryptoResult<file_operations::ExtractionResult> {
+        self.extract_archive_filtered(
+            decrypted_data,
+            output_path,
+            path_limit_strategy,
+            file_operations::RestoreFilter::default(),
+            None,
+            None,
+            None,
+        )
+    }
+
+    /// Extract only the entries selected by `restore_filter`
+    ///
+    /// Writes are paced by `io_pacer` when one is given. Files recorded in
+    /// `resume_journal` by an interrupted run are kept when still intact.
+    /// Archives whose manifest records `recorded_safety_limits` aren't
+    /// pre-scanned against the safety limits.
+    #[allow(clippy::too_many_arguments)]
+    #[instrument(skip(self, decrypted_data, io_pacer, resume_journal))]
+    pub fn extract_archive_filtered(
+        &self,
+        decrypted_data: &[u8],
+        output_path: &Path,
+        path_limit_strategy: file_operations::PathLimitStrategy,
+        restore_filter: file_operations::RestoreFilter,
+        io_pacer: Option<Arc<IoPacer>>,
+        resume_journal: Option<Arc<file_operations::ExtractionJournal>>,
+        recorded_safety_limits: Option<file_operations::ArchiveSafetyLimits>,
+    ) -> CryptoResult<file_operations::ExtractionResult> {
         debug!(
             decrypted_data_size = decrypted_data.len(),
             output_path = %output_path.display(),
@@ -50,25 +85,33 @@ pub fn extract_archive(
         );
 
         // Extract the archive
-        let config = file_operations::FileOpsConfig::default();
-        let extracted_files =
-            file_operations::extract_archive(&temp_archive_path, output_path, &config).map_err(
-                |e| {
+        let config = file_operations::FileOpsConfig {
+            path_limit_strategy,
+            restore_filter,
+            io_pacer,
+            resume_journal,
+            recorded_safety_limits,
+            ..file_operations::FileOpsConfig::default()
+        };
+        let extraction =
+            file_operations::extract_archive_with_report(&temp_archive_path, output_path, &config)
+                .map_err(|e| {
                     error!(error = %e, "Failed to extract archive");
                     CryptoError::DecryptionFailed(format!("Archive extraction failed: {}", e))
-                },
-            )?;
+                })?;
 
-        // Clean up temporary file (best effort)
-        let _ = std::fs::remove_file(&temp_archive_path);
+        // Clean up the decrypted TAR (best effort)
+        let _ = SecureDeleteService::new().delete_file(&temp_archive_path);
 
         info!(
-            extracted_files_count = extracted_files.len(),
+            extracted_files_count = extraction.files.len(),
+            renamed_count = extraction.renamed_paths.len(),
+            resumed_count = extraction.resumed,
             output_path = %output_path.display(),
             "Successfully extracted archive"
         );
 
-        Ok(extracted_files)
+        Ok(extraction)
     }
 
     /// Validate and create output directory using canonical method
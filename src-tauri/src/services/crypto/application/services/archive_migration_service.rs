@@ -0,0 +1,596 @@
+//! Archive Migration Service
+//!
+//! Re-encrypts a vault's current archive under new encryption parameters.
+//! The archive is decrypted with the usual credentials and unpacked to a
+//! staging directory, and every file is checked against the archive's
+//! manifest with that entry's own hash algorithm before anything is
+//! re-encrypted. The new archive replaces the old one like any other
+//! encryption; its index entry links back to the original, which can be
+//! marked eligible for pruning.
+//!
+//! The target compression and hash become the vault's parameters for later
+//! encryptions too, so the next routine backup doesn't undo the migration.
+
+use crate::prelude::*;
+use crate::services::crypto::application::services::{
+    DecryptionOrchestrationService, EmbeddedManifestService,
+};
+use crate::services::crypto::domain::models::{
+    ArchiveCompression, ArchiveMigrationNeed, ArchiveMigrationReport, EncryptionParameters,
+    MigrationEstimate, MigrationReason, ParameterMigrationPlan, migration_throughput,
+};
+use crate::services::crypto::domain::{CryptoError, CryptoResult};
+use crate::services::crypto::infrastructure::{
+    ArmoredOutputOptions, BenchmarkHistory, read_archive_preamble,
+};
+use crate::services::file::domain::models::ParityOptions;
+use crate::services::file::infrastructure::file_operations::{
+    FileOpsConfig, HashAlgorithm, extract_archive, restore_true_names,
+};
+use crate::services::shared::infrastructure::io::{OperationPriority, SecureTempFile};
+use crate::services::shared::infrastructure::{SecureDeleteService, get_vaults_directory};
+use crate::services::vault;
+use crate::services::vault::application::services::{
+    ArchiveService, MigrationLink, MigrationSource, VaultBundleEncryptionInput,
+    VaultBundleEncryptionService,
+};
+use crate::services::vault::domain::VaultError;
+use crate::services::vault::domain::models::ArchiveIndexEntry;
+use crate::services::vault::infrastructure::persistence::VaultSettingsRegistry;
+use crate::services::vault::infrastructure::persistence::metadata::VaultMetadata;
+use crate::types::ByteSize;
+use age::secrecy::SecretString;
+use std::path::Path;
+
+/// Service for migrating archives to new encryption parameters
+#[derive(Debug)]
+pub struct ArchiveMigrationService {
+    archive_service: ArchiveService,
+    embedded_manifest: EmbeddedManifestService,
+    vault_bundle_encryption: VaultBundleEncryptionService,
+    deleter: SecureDeleteService,
+}
+
+impl ArchiveMigrationService {
+    pub fn new() -> Self {
+        Self {
+            archive_service: ArchiveService::new(),
+            embedded_manifest: EmbeddedManifestService::new(),
+            vault_bundle_encryption: VaultBundleEncryptionService::new(),
+            deleter: SecureDeleteService::new(),
+        }
+    }
+
+    /// Report which of a vault's archives don't match `target`
+    ///
+    /// Reads only the index and local manifests; nothing is decrypted.
+    pub fn plan(
+        &self,
+        vault_id: &str,
+        target: &EncryptionParameters,
+    ) -> CryptoResult<ParameterMigrationPlan> {
+        target.validate().map_err(CryptoError::InvalidInput)?;
+        let entries = self
+            .archive_service
+            .list_archives(vault_id)
+            .map_err(|e| CryptoError::InvalidInput(format!("Archive index unavailable: {}", e)))?;
+        let vaults_dir = get_vaults_directory().map_err(|e| {
+            CryptoError::InvalidInput(format!("Failed to get vaults directory: {}", e))
+        })?;
+
+        let mut archives = Vec::new();
+        let mut up_to_date = 0;
+        let mut superseded = 0;
+        for entry in &entries {
+            if is_replaced(&entries, entry) {
+                superseded += 1;
+                continue;
+            }
+            let manifest = entry
+                .archive_name
+                .strip_suffix(".age")
+                .and_then(|name| self.embedded_manifest.load_external(name))
+                .filter(|manifest| manifest.encryption_revision() == entry.encryption_revision);
+            let reasons = match &manifest {
+                Some(manifest) => migration_reasons(manifest, target),
+                // No local manifest for this revision, so nothing shows it's current
+                None => vec![MigrationReason::ManifestSchema { version: None }],
+            };
+            if reasons.is_empty() {
+                up_to_date += 1;
+                continue;
+            }
+
+            archives.push(ArchiveMigrationNeed {
+                archive_id: entry.archive_id.clone(),
+                archive_name: entry.archive_name.clone(),
+                created_at: entry.created_at,
+                reasons,
+                blocked_reason: entry
+                    .immutable
+                    .then(|| "Marked immutable; clear the flag to migrate it".to_string()),
+                archive_size: std::fs::metadata(vaults_dir.join(&entry.archive_name))
+                    .map(|metadata| ByteSize(metadata.len()))
+                    .unwrap_or_default(),
+                content_size: ByteSize(manifest.map_or(0, |manifest| manifest.total_size())),
+            });
+        }
+
+        let throughput = BenchmarkHistory::load()
+            .ok()
+            .and_then(|history| history.results.last().and_then(migration_throughput));
+        let sizes: Vec<(ByteSize, ByteSize)> = archives
+            .iter()
+            .filter(|need| need.blocked_reason.is_none())
+            .map(|need| (need.archive_size, need.content_size))
+            .collect();
+
+        info!(
+            vault_id,
+            needing_migration = archives.len(),
+            up_to_date,
+            superseded,
+            "Planned parameter migration"
+        );
+        Ok(ParameterMigrationPlan {
+            vault_id: vault_id.to_string(),
+            target: target.clone(),
+            archives,
+            up_to_date,
+            superseded,
+            estimate: MigrationEstimate::new(&sizes, throughput),
+        })
+    }
+
+    /// Re-encrypt the archive at `archive_path` under `target`
+    ///
+    /// Nothing is written unless every file matches the archive's manifest.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn migrate(
+        &self,
+        decryption: &DecryptionOrchestrationService,
+        entry: &ArchiveIndexEntry,
+        archive_path: &Path,
+        target: &EncryptionParameters,
+        key_id: &str,
+        passphrase: SecretString,
+        prune_original: bool,
+    ) -> CryptoResult<ArchiveMigrationReport> {
+        target.validate().map_err(CryptoError::InvalidInput)?;
+        let vault = vault::load_vault(&entry.vault_id)
+            .await
+            .map_err(|e| CryptoError::InvalidInput(format!("Vault not found: {}", e)))?;
+
+        let (plaintext, resolution) =
+            decryption.decrypt_in_memory(&archive_path.to_string_lossy(), key_id, passphrase)?;
+        let manifest = resolution.preferred().cloned().ok_or_else(|| {
+            CryptoError::InvalidInput(
+                "This archive has no manifest to check its files against".to_string(),
+            )
+        })?;
+
+        let staging = tempfile::Builder::new()
+            .prefix("barqly-migrate-")
+            .tempdir()
+            .map_err(|e| CryptoError::IoError(format!("Failed to create staging: {}", e)))?;
+        let result = self
+            .reencrypt(
+                &vault,
+                entry,
+                archive_path,
+                &manifest,
+                &plaintext,
+                staging.path(),
+                target,
+                prune_original,
+            )
+            .await;
+        drop(plaintext);
+
+        if let Err(e) = self.deleter.delete_dir_all(staging.path()) {
+            warn!(error = %e, "Failed to securely delete migration staging");
+        }
+        result
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn reencrypt(
+        &self,
+        vault: &VaultMetadata,
+        entry: &ArchiveIndexEntry,
+        archive_path: &Path,
+        manifest: &VaultMetadata,
+        plaintext: &[u8],
+        staging: &Path,
+        target: &EncryptionParameters,
+        prune_original: bool,
+    ) -> CryptoResult<ArchiveMigrationReport> {
+        unpack_payload(manifest, plaintext, staging)?;
+        let verified_files = verify_staged_files(manifest, staging)?;
+        let (file_paths, source_root) = staged_selection(manifest, staging);
+
+        let parameters = EncryptionParameters {
+            contact_ids: None,
+            ..target.clone()
+        };
+        let contact_ids = target.contact_ids.clone().unwrap_or_else(|| {
+            manifest
+                .contacts()
+                .iter()
+                .map(|contact| contact.contact_id.clone())
+                .collect()
+        });
+        let armored_output = armored_output_like(archive_path);
+
+        let input = VaultBundleEncryptionInput {
+            vault_id: entry.vault_id.clone(),
+            vault_name: vault.label().to_string(),
+            file_paths,
+            source_root,
+            locked_file_policy: Default::default(),
+            resilient_source: None,
+            comment: entry.comment.clone(),
+            io_priority: OperationPriority::default(),
+            armored_output,
+            generate_parity: entry.parity.as_ref().map(|parity| ParityOptions {
+                redundancy_percent: parity.redundancy_percent,
+            }),
+            contact_ids,
+            strict: true,
+            parameters: parameters.clone(),
+            migration: Some(MigrationSource {
+                archive_id: entry.archive_id.clone(),
+                link: MigrationLink::Migrated { prune_original },
+            }),
+            output_dir: None,
+            archive_name: None,
+            transforms: Default::default(),
+        };
+        let result = self
+            .vault_bundle_encryption
+            .orchestrate_vault_encryption(input)
+            .await
+            .map_err(|e| match e {
+                VaultError::ArchiveImmutable { archive_name, .. } => {
+                    CryptoError::ArchiveImmutable { archive_name }
+                }
+                e => CryptoError::EncryptionFailed(format!("Migration failed: {}", e)),
+            })?;
+        let archive_id = result.archive_id.ok_or_else(|| {
+            CryptoError::IoError(
+                "The archive was re-encrypted but couldn't be recorded in the index".to_string(),
+            )
+        })?;
+
+        save_vault_parameters(&entry.vault_id, &parameters);
+
+        info!(
+            vault_id = %entry.vault_id,
+            original = %entry.archive_id,
+            archive_id = %archive_id,
+            hash_algorithm = %parameters.hash_algorithm,
+            verified_files,
+            "Migrated archive parameters"
+        );
+        Ok(ArchiveMigrationReport {
+            original_archive_id: entry.archive_id.clone(),
+            archive_id,
+            encrypted_file_path: result.encrypted_file_path,
+            parameters: target.clone(),
+            verified_files,
+            original_prune_marked: prune_original,
+        })
+    }
+}
+
+impl Default for ArchiveMigrationService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Parameters new archives of a vault are written with
+pub fn vault_parameters(vault_id: &str) -> EncryptionParameters {
+    match VaultSettingsRegistry::load() {
+        Ok(settings) => settings
+            .get(vault_id)
+            .encryption_parameters
+            .unwrap_or_default(),
+        Err(e) => {
+            warn!(error = %e, "Vault settings unavailable; using default encryption parameters");
+            EncryptionParameters::default()
+        }
+    }
+}
+
+fn save_vault_parameters(vault_id: &str, parameters: &EncryptionParameters) {
+    let saved = VaultSettingsRegistry::load().and_then(|mut settings| {
+        settings.entry(vault_id).encryption_parameters = Some(parameters.clone());
+        settings.save()
+    });
+    if let Err(e) = saved {
+        warn!(error = %e, "Failed to save the vault's encryption parameters (non-fatal)");
+    }
+}
+
+/// Why an archive with `manifest` doesn't match `target`; empty if it does
+pub fn migration_reasons(
+    manifest: &VaultMetadata,
+    target: &EncryptionParameters,
+) -> Vec<MigrationReason> {
+    let mut reasons = Vec::new();
+    if !manifest.records_hash_algorithms() {
+        reasons.push(MigrationReason::ManifestSchema {
+            version: manifest.schema_version(),
+        });
+    }
+
+    let mut current: Vec<HashAlgorithm> = Vec::new();
+    for file in &manifest.content.files {
+        if !current.contains(&file.hash_algorithm) {
+            current.push(file.hash_algorithm);
+        }
+    }
+    if current
+        .iter()
+        .any(|algorithm| *algorithm != target.hash_algorithm)
+    {
+        reasons.push(MigrationReason::HashAlgorithm { current });
+    }
+
+    // Every archive written so far is gzip
+    if target.compression != ArchiveCompression::Gzip {
+        reasons.push(MigrationReason::Compression {
+            current: ArchiveCompression::Gzip,
+        });
+    }
+
+    if let Some(target_ids) = &target.contact_ids {
+        let existing: Vec<&String> = manifest
+            .contacts()
+            .iter()
+            .map(|contact| &contact.contact_id)
+            .collect();
+        let added: Vec<String> = target_ids
+            .iter()
+            .filter(|id| !existing.contains(id))
+            .cloned()
+            .collect();
+        let removed: Vec<String> = existing
+            .into_iter()
+            .filter(|id| !target_ids.contains(*id))
+            .cloned()
+            .collect();
+        if !added.is_empty() || !removed.is_empty() {
+            reasons.push(MigrationReason::Recipients { added, removed });
+        }
+    }
+    reasons
+}
+
+/// Unpack a decrypted payload into `staging` under the files' true names
+pub fn unpack_payload(
+    manifest: &VaultMetadata,
+    plaintext: &[u8],
+    staging: &Path,
+) -> CryptoResult<()> {
+    let payload = SecureTempFile::new()
+        .map_err(|e| CryptoError::IoError(format!("Failed to create temp file: {}", e)))?;
+    std::fs::write(payload.path(), plaintext)
+        .map_err(|e| CryptoError::IoError(format!("Failed to stage payload: {}", e)))?;
+    let extracted = extract_archive(payload.path(), staging, &FileOpsConfig::default());
+    if let Err(e) = payload.secure_delete() {
+        warn!(error = %e, "Failed to securely delete staged payload");
+    }
+    let mut extracted =
+        extracted.map_err(|e| CryptoError::InvalidInput(format!("Failed to unpack: {}", e)))?;
+    restore_true_names(staging, &manifest.lossy_archived_paths(), &mut extracted)
+        .map_err(|e| CryptoError::IoError(format!("Failed to restore file names: {}", e)))?;
+    Ok(())
+}
+
+/// Armored output matching the archive at `archive_path`; binary if it isn't
+pub fn armored_output_like(archive_path: &Path) -> Option<ArmoredOutputOptions> {
+    read_archive_preamble(archive_path)
+        .ok()
+        .flatten()
+        .map(|preamble| ArmoredOutputOptions {
+            include_vault_name: preamble.vault_name.is_some(),
+        })
+}
+
+/// Check each unpacked file against its manifest entry, with the entry's
+/// own hash algorithm; returns how many were checked
+pub fn verify_staged_files(manifest: &VaultMetadata, staging: &Path) -> CryptoResult<usize> {
+    for entry in &manifest.content.files {
+        let path = staging.join(manifest.archived_file_path(entry));
+        let matches = entry.verify_file(&path).map_err(|e| {
+            CryptoError::InvalidInput(format!("Can't check '{}': {}", entry.path, e))
+        })?;
+        if !matches {
+            return Err(CryptoError::InvalidInput(format!(
+                "'{}' doesn't match its recorded {} digest",
+                entry.path, entry.hash_algorithm
+            )));
+        }
+    }
+    Ok(manifest.content.files.len())
+}
+
+/// Selection that re-encrypts the unpacked files as they were archived
+fn staged_selection(manifest: &VaultMetadata, staging: &Path) -> (Vec<String>, Option<String>) {
+    let root = manifest
+        .source_root()
+        .and_then(|root| Path::new(root).file_name())
+        .map(|name| name.to_string_lossy().to_string());
+    match root {
+        Some(root) => (
+            vec![staging.join(&root).to_string_lossy().to_string()],
+            Some(root),
+        ),
+        None => (
+            manifest
+                .content
+                .files
+                .iter()
+                .map(|entry| {
+                    staging
+                        .join(manifest.archived_file_path(entry))
+                        .to_string_lossy()
+                        .to_string()
+                })
+                .collect(),
+            None,
+        ),
+    }
+}
+
+/// A later encryption wrote over this entry's archive file
+fn is_replaced(entries: &[ArchiveIndexEntry], entry: &ArchiveIndexEntry) -> bool {
+    entries.iter().any(|other| {
+        other.archive_name == entry.archive_name
+            && other.encryption_revision > entry.encryption_revision
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::file::infrastructure::file_operations::{
+        RawPath, calculate_file_hash_with,
+    };
+    use crate::services::shared::infrastructure::DeviceInfo;
+    use crate::services::vault::infrastructure::persistence::metadata::{
+        MANIFEST_SCHEMA, VaultFileEntry,
+    };
+    use chrono::Utc;
+    use tempfile::TempDir;
+
+    /// A gzip/SHA-256 manifest as written before hash algorithms were recorded
+    fn legacy_manifest(root: &Path, files: &[(&str, &[u8])]) -> VaultMetadata {
+        let device_info = DeviceInfo {
+            machine_id: "machine-1".to_string(),
+            machine_label: "laptop".to_string(),
+            created_at: Utc::now(),
+            app_version: "0.2.0".to_string(),
+        };
+        let entries = files
+            .iter()
+            .map(|(path, content)| {
+                let staged = root.join("Records").join(path);
+                std::fs::create_dir_all(staged.parent().unwrap()).unwrap();
+                std::fs::write(&staged, content).unwrap();
+                VaultFileEntry {
+                    path: path.to_string(),
+                    raw_path: RawPath::from_display(path),
+                    lossy_name: false,
+                    size: content.len() as u64,
+                    sha256: calculate_file_hash_with(&staged, HashAlgorithm::Sha256).unwrap(),
+                    hash_algorithm: HashAlgorithm::Sha256,
+                    digest: None,
+                    ownership: None,
+                    content_type: None,
+                    content_type_mismatch: false,
+                    transform: None,
+                }
+            })
+            .collect();
+        let manifest = VaultMetadata::new(
+            "vault-001".to_string(),
+            "Family".to_string(),
+            None,
+            "Family".to_string(),
+            &device_info,
+            Some("Records".to_string()),
+            vec![],
+            entries,
+            files.len(),
+            0,
+        );
+
+        let mut json = serde_json::to_value(&manifest).unwrap();
+        json["schema"] = "barqly.vault.manifest/3".into();
+        for file in json["content"]["files"].as_array_mut().unwrap() {
+            file.as_object_mut().unwrap().remove("hash_algorithm");
+        }
+        serde_json::from_value(json).unwrap()
+    }
+
+    #[test]
+    fn test_legacy_archive_needs_migration_to_new_parameters() {
+        let temp = TempDir::new().unwrap();
+        let manifest = legacy_manifest(temp.path(), &[("deed.pdf", b"deed")]);
+
+        let target = EncryptionParameters {
+            compression: ArchiveCompression::Zstd,
+            hash_algorithm: HashAlgorithm::Sha512,
+            contact_ids: Some(vec!["contact-sam".to_string()]),
+            ..EncryptionParameters::default()
+        };
+        assert_eq!(
+            migration_reasons(&manifest, &target),
+            vec![
+                MigrationReason::ManifestSchema { version: Some(3) },
+                MigrationReason::HashAlgorithm {
+                    current: vec![HashAlgorithm::Sha256]
+                },
+                MigrationReason::Compression {
+                    current: ArchiveCompression::Gzip
+                },
+                MigrationReason::Recipients {
+                    added: vec!["contact-sam".to_string()],
+                    removed: vec![],
+                },
+            ]
+        );
+
+        // Once re-manifested under the current schema, SHA-256 is up to date
+        let mut current = manifest.clone();
+        current.schema = MANIFEST_SCHEMA.to_string();
+        assert!(migration_reasons(&current, &EncryptionParameters::default()).is_empty());
+    }
+
+    #[test]
+    fn test_staged_files_verify_with_each_entry_algorithm() {
+        let temp = TempDir::new().unwrap();
+        let mut manifest = legacy_manifest(
+            temp.path(),
+            &[("deed.pdf", b"deed"), ("letters/1952.txt", b"Dear Ada")],
+        );
+        assert_eq!(verify_staged_files(&manifest, temp.path()).unwrap(), 2);
+
+        // One entry migrated to SHA-512; each is checked with its own algorithm
+        let staged = temp.path().join("Records").join("letters/1952.txt");
+        manifest.content.files[1]
+            .rehash(&staged, HashAlgorithm::Sha512)
+            .unwrap();
+        assert_eq!(verify_staged_files(&manifest, temp.path()).unwrap(), 2);
+
+        // A SHA-256 digest recorded as SHA-512 doesn't pass
+        let file = &mut manifest.content.files[1];
+        file.digest = Some(file.sha256.clone());
+        let error = verify_staged_files(&manifest, temp.path()).unwrap_err();
+        assert!(error.to_string().contains("sha512"));
+    }
+
+    #[test]
+    fn test_staged_selection_matches_the_original() {
+        let temp = TempDir::new().unwrap();
+        let manifest = legacy_manifest(temp.path(), &[("deed.pdf", b"deed")]);
+        let (paths, root) = staged_selection(&manifest, temp.path());
+        assert_eq!(root.as_deref(), Some("Records"));
+        assert_eq!(
+            paths,
+            vec![temp.path().join("Records").to_string_lossy().to_string()]
+        );
+
+        let mut files = manifest.clone();
+        files.content.source_root = None;
+        let (paths, root) = staged_selection(&files, temp.path());
+        assert_eq!(root, None);
+        assert_eq!(
+            paths,
+            vec![temp.path().join("deed.pdf").to_string_lossy().to_string()]
+        );
+    }
+}
@@ -122,6 +122,7 @@ fn test_create_file_selection_single_folder() {
             file_paths: vec!["/tmp".to_string()], // This would be a directory in real scenario
             output_name: None,
             output_path: None,
+            contact_ids: vec![],
         };
 
         // This test verifies the logic works, though /tmp might not exist in test env
@@ -137,6 +138,7 @@ fn test_create_file_selection_multiple_files() {
             file_paths: vec!["file1.txt".to_string(), "file2.txt".to_string()],
             output_name: None,
             output_path: None,
+            contact_ids: vec![],
         };
 
         let _result = service.create_file_selection_from_input(&input);
@@ -0,0 +1,413 @@
+//! Archive Path Remap Service
+//!
+//! Rewrites the internal paths of a vault's current archive after a source
+//! folder was renamed. The archive is decrypted and unpacked to a staging
+//! directory and checked against its manifest, as for a parameter migration;
+//! the files are then moved to their remapped paths and re-encrypted, which
+//! writes a fresh manifest with the new paths. The new archive is written
+//! beside the original under a `-remapped` name and linked back to it with
+//! `remapped_from`; the original archive and its index entry are left as
+//! they were.
+
+use crate::prelude::*;
+use crate::services::crypto::application::services::DecryptionOrchestrationService;
+use crate::services::crypto::application::services::archive_migration_service::{
+    armored_output_like, unpack_payload, vault_parameters, verify_staged_files,
+};
+use crate::services::crypto::domain::models::{
+    ArchiveRemapReport, EncryptionParameters, PathPrefixMapping, PathRemapPlan, plan_path_remap,
+};
+use crate::services::crypto::domain::{CryptoError, CryptoResult};
+use crate::services::file::domain::models::ParityOptions;
+use crate::services::shared::infrastructure::SecureDeleteService;
+use crate::services::shared::infrastructure::io::OperationPriority;
+use crate::services::vault;
+use crate::services::vault::application::services::{
+    MigrationLink, MigrationSource, VaultBundleEncryptionInput, VaultBundleEncryptionService,
+};
+use crate::services::vault::domain::VaultError;
+use crate::services::vault::domain::models::{ARCHIVE_EXTENSION, ArchiveIndexEntry};
+use crate::services::vault::infrastructure::persistence::metadata::VaultMetadata;
+use age::secrecy::SecretString;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Service for remapping the paths inside archives
+#[derive(Debug)]
+pub struct ArchivePathRemapService {
+    vault_bundle_encryption: VaultBundleEncryptionService,
+    deleter: SecureDeleteService,
+}
+
+impl ArchivePathRemapService {
+    pub fn new() -> Self {
+        Self {
+            vault_bundle_encryption: VaultBundleEncryptionService::new(),
+            deleter: SecureDeleteService::new(),
+        }
+    }
+
+    /// Re-encrypt the archive at `archive_path` with its paths remapped
+    ///
+    /// The mappings are checked against the manifest before anything is
+    /// unpacked, and nothing is written unless every file matches it.
+    pub async fn remap(
+        &self,
+        decryption: &DecryptionOrchestrationService,
+        entry: &ArchiveIndexEntry,
+        archive_path: &Path,
+        mappings: &[PathPrefixMapping],
+        key_id: &str,
+        passphrase: SecretString,
+    ) -> CryptoResult<ArchiveRemapReport> {
+        let vault = vault::load_vault(&entry.vault_id)
+            .await
+            .map_err(|e| CryptoError::InvalidInput(format!("Vault not found: {}", e)))?;
+
+        let (plaintext, resolution) =
+            decryption.decrypt_in_memory(&archive_path.to_string_lossy(), key_id, passphrase)?;
+        let manifest = resolution.preferred().cloned().ok_or_else(|| {
+            CryptoError::InvalidInput(
+                "This archive has no manifest to find its paths in".to_string(),
+            )
+        })?;
+        let plan = plan_manifest_remap(&manifest, mappings)?;
+
+        let staging = tempfile::Builder::new()
+            .prefix("barqly-remap-")
+            .tempdir()
+            .map_err(|e| CryptoError::IoError(format!("Failed to create staging: {}", e)))?;
+        let result = self
+            .reencrypt(
+                &vault,
+                entry,
+                archive_path,
+                &manifest,
+                &plan,
+                &plaintext,
+                staging.path(),
+            )
+            .await;
+        drop(plaintext);
+
+        if let Err(e) = self.deleter.delete_dir_all(staging.path()) {
+            warn!(error = %e, "Failed to securely delete remap staging");
+        }
+        result
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn reencrypt(
+        &self,
+        vault: &VaultMetadata,
+        entry: &ArchiveIndexEntry,
+        archive_path: &Path,
+        manifest: &VaultMetadata,
+        plan: &PathRemapPlan,
+        plaintext: &[u8],
+        staging: &Path,
+    ) -> CryptoResult<ArchiveRemapReport> {
+        let extracted = staging.join("extracted");
+        let remapped = staging.join("remapped");
+        unpack_payload(manifest, plaintext, &extracted)?;
+        let verified_files = verify_staged_files(manifest, &extracted)?;
+        stage_remapped(plan, &extracted, &remapped)?;
+        let (file_paths, source_root) = remapped_selection(manifest, plan, &remapped);
+
+        let parameters = EncryptionParameters {
+            contact_ids: None,
+            ..vault_parameters(&entry.vault_id)
+        };
+        let archive_name = remapped_archive_name(&entry.archive_name);
+        let input = VaultBundleEncryptionInput {
+            vault_id: entry.vault_id.clone(),
+            vault_name: vault.label().to_string(),
+            file_paths,
+            source_root,
+            locked_file_policy: Default::default(),
+            resilient_source: None,
+            comment: entry.comment.clone(),
+            io_priority: OperationPriority::default(),
+            armored_output: armored_output_like(archive_path),
+            generate_parity: entry.parity.as_ref().map(|parity| ParityOptions {
+                redundancy_percent: parity.redundancy_percent,
+            }),
+            contact_ids: manifest
+                .contacts()
+                .iter()
+                .map(|contact| contact.contact_id.clone())
+                .collect(),
+            strict: true,
+            parameters,
+            migration: Some(MigrationSource {
+                archive_id: entry.archive_id.clone(),
+                link: MigrationLink::Remapped,
+            }),
+            output_dir: None,
+            archive_name: Some(archive_name),
+            transforms: Default::default(),
+        };
+        let result = self
+            .vault_bundle_encryption
+            .orchestrate_vault_encryption(input)
+            .await
+            .map_err(|e| match e {
+                VaultError::ArchiveImmutable { archive_name, .. } => {
+                    CryptoError::ArchiveImmutable { archive_name }
+                }
+                e => CryptoError::EncryptionFailed(format!("Remap failed: {}", e)),
+            })?;
+        let archive_id = result.archive_id.ok_or_else(|| {
+            CryptoError::IoError(
+                "The archive was re-encrypted but couldn't be recorded in the index".to_string(),
+            )
+        })?;
+
+        info!(
+            vault_id = %entry.vault_id,
+            original = %entry.archive_id,
+            archive_id = %archive_id,
+            remapped = plan.remapped,
+            unchanged = plan.unchanged(),
+            verified_files,
+            "Remapped archive paths"
+        );
+        Ok(ArchiveRemapReport {
+            original_archive_id: entry.archive_id.clone(),
+            archive_id,
+            encrypted_file_path: result.encrypted_file_path,
+            remapped_entries: plan.remapped,
+            unchanged_entries: plan.unchanged(),
+            verified_files,
+        })
+    }
+}
+
+impl Default for ArchivePathRemapService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Plan `mappings` over every file and folder the manifest records,
+/// including the folder the archive is rooted at
+pub fn plan_manifest_remap(
+    manifest: &VaultMetadata,
+    mappings: &[PathPrefixMapping],
+) -> CryptoResult<PathRemapPlan> {
+    let root = archive_root(manifest);
+    let paths: Vec<PathBuf> = root
+        .iter()
+        .cloned()
+        .chain(manifest.archived_directory_paths().unwrap_or_default())
+        .chain(
+            manifest
+                .content
+                .files
+                .iter()
+                .map(|entry| manifest.archived_file_path(entry)),
+        )
+        .filter(|path| !path.as_os_str().is_empty())
+        .collect();
+    plan_path_remap(&paths, mappings, root.is_some()).map_err(CryptoError::InvalidInput)
+}
+
+/// Move unpacked files from `extracted` to their new paths under `remapped`
+///
+/// Folders are created rather than moved, so each file moves exactly once
+/// and its content is never rewritten.
+pub fn stage_remapped(plan: &PathRemapPlan, extracted: &Path, remapped: &Path) -> CryptoResult<()> {
+    for (old, new) in &plan.moves {
+        let from = extracted.join(old);
+        let to = remapped.join(new);
+        if from.is_dir() {
+            fs::create_dir_all(&to).map_err(|e| {
+                CryptoError::IoError(format!("Failed to create '{}': {}", new.display(), e))
+            })?;
+            continue;
+        }
+        if let Some(parent) = to.parent() {
+            fs::create_dir_all(parent).map_err(|e| {
+                CryptoError::IoError(format!("Failed to create '{}': {}", parent.display(), e))
+            })?;
+        }
+        fs::rename(&from, &to).map_err(|e| {
+            CryptoError::IoError(format!(
+                "Failed to move '{}' to '{}': {}",
+                old.display(),
+                new.display(),
+                e
+            ))
+        })?;
+    }
+    Ok(())
+}
+
+/// Folder a folder archive is rooted at, as archived
+fn archive_root(manifest: &VaultMetadata) -> Option<PathBuf> {
+    manifest
+        .source_root()
+        .and_then(|root| Path::new(root).file_name())
+        .map(PathBuf::from)
+}
+
+/// Selection that re-encrypts the remapped files with the archive's layout
+fn remapped_selection(
+    manifest: &VaultMetadata,
+    plan: &PathRemapPlan,
+    remapped: &Path,
+) -> (Vec<String>, Option<String>) {
+    let new_root = archive_root(manifest)
+        .and(plan.moves.first())
+        .and_then(|(_, new)| new.iter().next())
+        .map(|root| root.to_string_lossy().to_string());
+    match new_root {
+        Some(root) => (
+            vec![remapped.join(&root).to_string_lossy().to_string()],
+            Some(root),
+        ),
+        None => (
+            plan.moves
+                .iter()
+                .map(|(_, new)| remapped.join(new).to_string_lossy().to_string())
+                .collect(),
+            None,
+        ),
+    }
+}
+
+/// File name for a remapped copy of the archive `archive_name`
+///
+/// e.g. `Taxes.age` → `Taxes-remapped.age`
+fn remapped_archive_name(archive_name: &str) -> String {
+    let stem = archive_name
+        .strip_suffix(ARCHIVE_EXTENSION)
+        .unwrap_or(archive_name);
+    format!("{stem}-remapped{ARCHIVE_EXTENSION}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::file::infrastructure::file_operations::{
+        HashAlgorithm, RawPath, calculate_file_hash_with,
+    };
+    use crate::services::shared::infrastructure::DeviceInfo;
+    use crate::services::vault::infrastructure::persistence::metadata::VaultFileEntry;
+    use chrono::Utc;
+    use tempfile::TempDir;
+
+    /// A `Taxes` folder archive unpacked into `extracted`
+    fn unpacked(extracted: &Path, files: &[(&str, &[u8])]) -> VaultMetadata {
+        let device_info = DeviceInfo {
+            machine_id: "machine-1".to_string(),
+            machine_label: "laptop".to_string(),
+            created_at: Utc::now(),
+            app_version: "0.2.0".to_string(),
+        };
+        let entries = files
+            .iter()
+            .map(|(path, content)| {
+                let staged = extracted.join("Taxes").join(path);
+                fs::create_dir_all(staged.parent().unwrap()).unwrap();
+                fs::write(&staged, content).unwrap();
+                VaultFileEntry {
+                    path: path.to_string(),
+                    raw_path: RawPath::from_display(path),
+                    lossy_name: false,
+                    size: content.len() as u64,
+                    sha256: calculate_file_hash_with(&staged, HashAlgorithm::Sha256).unwrap(),
+                    hash_algorithm: HashAlgorithm::Sha256,
+                    digest: None,
+                    ownership: None,
+                    content_type: None,
+                    content_type_mismatch: false,
+                    transform: None,
+                }
+            })
+            .collect();
+        VaultMetadata::new(
+            "vault-001".to_string(),
+            "Family".to_string(),
+            None,
+            "Family".to_string(),
+            &device_info,
+            Some("/Users/sam/Documents/Taxes".to_string()),
+            vec![],
+            entries,
+            files.len(),
+            0,
+        )
+    }
+
+    fn mapping(from: &str, to: &str) -> PathPrefixMapping {
+        PathPrefixMapping {
+            from_prefix: from.to_string(),
+            to_prefix: to.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_remap_with_nested_prefixes_keeps_contents() {
+        let temp = TempDir::new().unwrap();
+        let (extracted, remapped) = (temp.path().join("extracted"), temp.path().join("remapped"));
+        let mut manifest = unpacked(
+            &extracted,
+            &[
+                ("2023/return.pdf", b"return 2023"),
+                ("2024/return.pdf", b"return 2024"),
+                ("Taxi/receipt.pdf", b"fare"),
+            ],
+        );
+        assert_eq!(verify_staged_files(&manifest, &extracted).unwrap(), 3);
+
+        let plan = plan_manifest_remap(
+            &manifest,
+            &[
+                mapping("Taxes", "Finanzen"),
+                mapping("Taxes/2023", "Finanzen/Steuer-2023"),
+            ],
+        )
+        .unwrap();
+        stage_remapped(&plan, &extracted, &remapped).unwrap();
+
+        let (paths, root) = remapped_selection(&manifest, &plan, &remapped);
+        assert_eq!(root.as_deref(), Some("Finanzen"));
+        assert_eq!(
+            paths,
+            vec![remapped.join("Finanzen").to_string_lossy().to_string()]
+        );
+        for (path, content) in [
+            ("Finanzen/Steuer-2023/return.pdf", &b"return 2023"[..]),
+            ("Finanzen/2024/return.pdf", b"return 2024"),
+            ("Finanzen/Taxi/receipt.pdf", b"fare"),
+        ] {
+            assert_eq!(fs::read(remapped.join(path)).unwrap(), content, "{path}");
+        }
+        assert!(!extracted.join("Taxes/2024/return.pdf").exists());
+
+        // Files the mappings only moved still match their recorded digests
+        manifest.content.source_root = Some("Finanzen".to_string());
+        manifest.content.files.remove(0);
+        assert_eq!(verify_staged_files(&manifest, &remapped).unwrap(), 2);
+    }
+
+    #[test]
+    fn test_colliding_remap_fails_before_writing() {
+        let temp = TempDir::new().unwrap();
+        let (extracted, remapped) = (temp.path().join("extracted"), temp.path().join("remapped"));
+        let manifest = unpacked(
+            &extracted,
+            &[("2023/return.pdf", b"old"), ("2024/return.pdf", b"new")],
+        );
+
+        let error =
+            plan_manifest_remap(&manifest, &[mapping("Taxes/2023", "Taxes/2024")]).unwrap_err();
+        assert!(matches!(error, CryptoError::InvalidInput(_)));
+        assert!(error.to_string().contains("would both become"));
+        assert!(!remapped.exists());
+        assert_eq!(verify_staged_files(&manifest, &extracted).unwrap(), 2);
+
+        assert_eq!(remapped_archive_name("Taxes.age"), "Taxes-remapped.age");
+    }
+}
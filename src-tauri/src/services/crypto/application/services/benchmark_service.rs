@@ -0,0 +1,363 @@
+//! Throughput benchmark service
+//!
+//! Generates synthetic files in a temporary directory and times them through
+//! the same staging, archive, age encryption, decryption and extraction code
+//! a real vault uses, with a throwaway key. Nothing outside the temporary
+//! directory is read or written apart from the benchmark history, and the
+//! directory is removed when the run ends, whether it succeeded or not.
+
+use crate::prelude::*;
+use crate::services::crypto::domain::models::{
+    BenchmarkPhase, BenchmarkProfile, BenchmarkResult, BenchmarkSizes, OperationPhase,
+    PhaseThroughput, PhaseTimer,
+};
+use crate::services::crypto::domain::{CryptoError, CryptoResult};
+use crate::services::crypto::infrastructure::{self as crypto, BenchmarkHistory};
+use crate::services::file::infrastructure::file_operations::{
+    FileInfo, FileOpsConfig, FileSelection, StagingArea, archive_operations::create_tar_gz,
+    extract_archive,
+};
+use crate::services::key_management::passphrase::infrastructure::generate_keypair;
+use crate::types::ByteSize;
+use chrono::Utc;
+use rand::RngCore;
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+/// Synthetic files are written in chunks of this size
+const CHUNK_SIZE: usize = 1024 * 1024;
+const FILL_PATTERN: &[u8] = b"barqly vault benchmark ";
+
+/// Benchmark phases in the order they run
+const PHASES: [BenchmarkPhase; 5] = [
+    BenchmarkPhase::Hash,
+    BenchmarkPhase::Compress,
+    BenchmarkPhase::Encrypt,
+    BenchmarkPhase::Decrypt,
+    BenchmarkPhase::Verify,
+];
+
+#[derive(Debug, Default)]
+pub struct BenchmarkService;
+
+impl BenchmarkService {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Run a benchmark and add the result to the benchmark history
+    ///
+    /// Blocking; callers on the async runtime should use `spawn_blocking`.
+    pub fn run(
+        &self,
+        profile: BenchmarkProfile,
+        sizes: BenchmarkSizes,
+    ) -> CryptoResult<BenchmarkResult> {
+        let result = self.measure(profile, sizes)?;
+
+        BenchmarkHistory::append(result.clone())
+            .map_err(|e| CryptoError::IoError(format!("Failed to save benchmark: {e}")))?;
+
+        Ok(result)
+    }
+
+    /// Run a benchmark without recording it
+    #[instrument(skip(self))]
+    pub fn measure(
+        &self,
+        profile: BenchmarkProfile,
+        sizes: BenchmarkSizes,
+    ) -> CryptoResult<BenchmarkResult> {
+        if sizes.total() == ByteSize::ZERO {
+            return Err(CryptoError::InvalidInput(
+                "Benchmark needs at least one non-empty file".to_string(),
+            ));
+        }
+
+        let started_at = Utc::now();
+        let run_timer = Instant::now();
+
+        let work_dir = tempfile::tempdir()
+            .map_err(|e| CryptoError::IoError(format!("Failed to create work directory: {e}")))?;
+        let source_dir = work_dir.path().join("source");
+        let file_count = generate_files(&source_dir, &sizes)
+            .map_err(|e| CryptoError::IoError(format!("Failed to generate test data: {e}")))?;
+        let input_size = sizes.total();
+        let keypair = generate_keypair()
+            .map_err(|e| CryptoError::InvalidKey(format!("Failed to generate key: {e}")))?;
+        let config = FileOpsConfig::default();
+
+        info!(?profile, file_count, input_size = %input_size, "Starting benchmark");
+
+        // Backup: stage and hash, archive, encrypt
+        let backup_timer = Instant::now();
+        let mut timer = PhaseTimer::start();
+        let phase = timer.begin(OperationPhase::Hashing);
+        let mut staging = StagingArea::new()
+            .map_err(|e| CryptoError::IoError(format!("Failed to create staging area: {e}")))?;
+        staging
+            .stage_files(&FileSelection::Folder(source_dir))
+            .map_err(|e| CryptoError::IoError(format!("Failed to stage test data: {e}")))?;
+        timer.end(phase, input_size.bytes());
+
+        let archive_path = work_dir.path().join("benchmark.tar.gz");
+        let phase = timer.begin(OperationPhase::Archiving);
+        let archive_info = create_tar_gz(&staging, &archive_path, &config)
+            .map_err(|e| CryptoError::EncryptionFailed(format!("Failed to create archive: {e}")))?;
+        timer.end(phase, input_size.bytes());
+        let archive_size = ByteSize(archive_info.compressed_size);
+
+        let encrypted_path = work_dir.path().join("benchmark.age");
+        let phase = timer.begin(OperationPhase::Encryption);
+        let archive_data = fs::read(&archive_path).map_err(io_error)?;
+        let encrypted = crypto::encrypt_data(&archive_data, &keypair.public_key)
+            .map_err(|e| CryptoError::EncryptionFailed(e.to_string()))?;
+        drop(archive_data);
+        fs::write(&encrypted_path, &encrypted).map_err(io_error)?;
+        drop(encrypted);
+        timer.end(phase, archive_size.bytes());
+        let backup_elapsed = backup_timer.elapsed();
+
+        // Restore: decrypt, extract and check every file
+        let restore_timer = Instant::now();
+        let decrypted_path = work_dir.path().join("decrypted.tar.gz");
+        let phase = timer.begin(OperationPhase::Decryption);
+        let encrypted = fs::read(&encrypted_path).map_err(io_error)?;
+        let decrypted = crypto::decrypt_data(&encrypted, &keypair.private_key)
+            .map_err(|e| CryptoError::DecryptionFailed(e.to_string()))?;
+        drop(encrypted);
+        fs::write(&decrypted_path, &decrypted).map_err(io_error)?;
+        drop(decrypted);
+        timer.end(phase, archive_size.bytes());
+
+        let restore_dir = work_dir.path().join("restored");
+        let phase = timer.begin(OperationPhase::Verification);
+        let extracted = extract_archive(&decrypted_path, &restore_dir, &config).map_err(|e| {
+            CryptoError::DecryptionFailed(format!("Failed to extract archive: {e}"))
+        })?;
+        verify_round_trip(
+            &file_hashes(staging.staged_files(), staging.path()),
+            &file_hashes(&extracted, &restore_dir),
+        )?;
+        timer.end(phase, input_size.bytes());
+        let restore_elapsed = restore_timer.elapsed();
+        let timing = timer.finish();
+
+        drop(staging);
+        work_dir
+            .close()
+            .map_err(|e| CryptoError::IoError(format!("Failed to remove test data: {e}")))?;
+
+        let result = BenchmarkResult {
+            benchmark_id: uuid::Uuid::new_v4().to_string(),
+            profile,
+            sizes,
+            started_at,
+            file_count,
+            input_size,
+            archive_size,
+            phases: timing
+                .phases
+                .iter()
+                .zip(PHASES)
+                .map(|(timing, phase)| PhaseThroughput::from_timing(phase, timing))
+                .collect(),
+            encrypt_duration_ms: backup_elapsed.into(),
+            decrypt_duration_ms: restore_elapsed.into(),
+            total_duration_ms: run_timer.elapsed().into(),
+            peak_memory: peak_resident_memory(),
+        };
+
+        info!(
+            benchmark_id = %result.benchmark_id,
+            total_duration = %result.total_duration_ms,
+            "Benchmark completed"
+        );
+
+        Ok(result)
+    }
+}
+
+fn io_error(e: io::Error) -> CryptoError {
+    CryptoError::IoError(e.to_string())
+}
+
+/// Write the synthetic data set, returning the number of files
+fn generate_files(dir: &Path, sizes: &BenchmarkSizes) -> io::Result<u32> {
+    let mut rng = rand::thread_rng();
+    let mut chunk = vec![0u8; CHUNK_SIZE];
+
+    let small_dir = dir.join("small");
+    fs::create_dir_all(&small_dir)?;
+    for i in 0..sizes.small_file_count {
+        let path = small_dir.join(format!("file_{i:05}.bin"));
+        write_file(&path, sizes.small_file_size.0, &mut rng, &mut chunk)?;
+    }
+
+    if sizes.large_file_size == ByteSize::ZERO {
+        return Ok(sizes.small_file_count);
+    }
+    write_file(
+        &dir.join("large.bin"),
+        sizes.large_file_size.0,
+        &mut rng,
+        &mut chunk,
+    )?;
+    Ok(sizes.small_file_count + 1)
+}
+
+/// Half random bytes and half a repeated phrase per chunk, so compression
+/// has real work to do without the data being incompressible
+fn write_file(path: &Path, size: u64, rng: &mut impl RngCore, chunk: &mut [u8]) -> io::Result<()> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    let mut remaining = size;
+    while remaining > 0 {
+        let len = remaining.min(chunk.len() as u64) as usize;
+        let (noise, pattern) = chunk[..len].split_at_mut(len / 2);
+        rng.fill_bytes(noise);
+        for (i, byte) in pattern.iter_mut().enumerate() {
+            *byte = FILL_PATTERN[i % FILL_PATTERN.len()];
+        }
+        writer.write_all(&chunk[..len])?;
+        remaining -= len as u64;
+    }
+    writer.flush()
+}
+
+/// Hash of each file keyed by its path below `root`
+fn file_hashes(files: &[FileInfo], root: &Path) -> BTreeMap<PathBuf, String> {
+    files
+        .iter()
+        .map(|file| {
+            let relative = file.path.strip_prefix(root).unwrap_or(&file.path);
+            (relative.to_path_buf(), file.hash.clone())
+        })
+        .collect()
+}
+
+fn verify_round_trip(
+    original: &BTreeMap<PathBuf, String>,
+    restored: &BTreeMap<PathBuf, String>,
+) -> CryptoResult<()> {
+    if original.len() != restored.len() {
+        return Err(CryptoError::DecryptionFailed(format!(
+            "Benchmark restored {} of {} files",
+            restored.len(),
+            original.len()
+        )));
+    }
+
+    match original
+        .iter()
+        .find(|(path, hash)| restored.get(*path) != Some(*hash))
+    {
+        Some((path, _)) => Err(CryptoError::DecryptionFailed(format!(
+            "Benchmark file {} didn't survive the round trip",
+            path.display()
+        ))),
+        None => Ok(()),
+    }
+}
+
+/// Process peak resident set size (`VmHWM`)
+#[cfg(target_os = "linux")]
+fn peak_resident_memory() -> Option<ByteSize> {
+    let status = fs::read_to_string("/proc/self/status").ok()?;
+    let kilobytes: u64 = status
+        .lines()
+        .find_map(|line| line.strip_prefix("VmHWM:"))?
+        .trim()
+        .trim_end_matches("kB")
+        .trim()
+        .parse()
+        .ok()?;
+    Some(ByteSize(kilobytes * 1024))
+}
+
+#[cfg(not(target_os = "linux"))]
+fn peak_resident_memory() -> Option<ByteSize> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tiny_sizes() -> BenchmarkSizes {
+        BenchmarkSizes {
+            small_file_count: 5,
+            small_file_size: ByteSize(4 * 1024),
+            large_file_size: ByteSize(256 * 1024),
+        }
+    }
+
+    #[test]
+    fn test_quick_profile_round_trip() {
+        let sizes = tiny_sizes();
+        let result = BenchmarkService::new()
+            .measure(BenchmarkProfile::Quick, sizes)
+            .unwrap();
+
+        assert_eq!(result.file_count, 6);
+        assert_eq!(result.input_size, ByteSize(5 * 4 * 1024 + 256 * 1024));
+        assert!(result.archive_size > ByteSize::ZERO);
+        assert!(result.archive_size < result.input_size);
+
+        let phases: Vec<_> = result.phases.iter().map(|p| p.phase).collect();
+        assert_eq!(
+            phases,
+            vec![
+                BenchmarkPhase::Hash,
+                BenchmarkPhase::Compress,
+                BenchmarkPhase::Encrypt,
+                BenchmarkPhase::Decrypt,
+                BenchmarkPhase::Verify,
+            ]
+        );
+        assert_eq!(
+            result.phase(BenchmarkPhase::Encrypt).unwrap().bytes,
+            result.archive_size
+        );
+        assert!(result.total_duration_ms >= result.encrypt_duration_ms);
+        #[cfg(target_os = "linux")]
+        assert!(result.peak_memory.is_some());
+    }
+
+    #[test]
+    fn test_small_files_only() {
+        let sizes = BenchmarkSizes {
+            large_file_size: ByteSize::ZERO,
+            ..tiny_sizes()
+        };
+        let result = BenchmarkService::new()
+            .measure(BenchmarkProfile::Quick, sizes)
+            .unwrap();
+        assert_eq!(result.file_count, 5);
+    }
+
+    #[test]
+    fn test_empty_data_set_rejected() {
+        let sizes = BenchmarkSizes {
+            small_file_count: 0,
+            small_file_size: ByteSize(1024),
+            large_file_size: ByteSize::ZERO,
+        };
+        assert!(matches!(
+            BenchmarkService::new().measure(BenchmarkProfile::Quick, sizes),
+            Err(CryptoError::InvalidInput(_))
+        ));
+    }
+
+    #[test]
+    fn test_verify_detects_changed_file() {
+        let original = BTreeMap::from([(PathBuf::from("a.bin"), "aa".to_string())]);
+        let changed = BTreeMap::from([(PathBuf::from("a.bin"), "bb".to_string())]);
+
+        assert!(verify_round_trip(&original, &original).is_ok());
+        assert!(verify_round_trip(&original, &changed).is_err());
+        assert!(verify_round_trip(&original, &BTreeMap::new()).is_err());
+    }
+}
@@ -0,0 +1,572 @@
+//! Scheduled cleanup of decrypted output
+//!
+//! A decryption with a session TTL registers its output directory here. Once
+//! the deadline passes, `run_due` deletes each extracted file through
+//! `SecureDeleteService`, then removes directories left empty. The outcome
+//! says how far the deletion can be trusted: SSDs, copy-on-write filesystems
+//! and synced folders may keep old copies.
+//!
+//! Cleanup only deletes what it extracted. If the directory was moved, holds
+//! files that weren't extracted, or an extracted file was edited, the
+//! session is dropped without touching anything and reported as refused.
+
+use crate::services::crypto::domain::models::{
+    CleanupFile, CleanupOutcome, CleanupRefusal, CleanupResult, CleanupSession,
+    CleanupSessionSummary,
+};
+use crate::services::crypto::domain::{CryptoError, CryptoResult};
+use crate::services::crypto::infrastructure::CleanupSessionStore;
+use crate::services::file::infrastructure::file_operations::FileInfo;
+use crate::services::shared::infrastructure::path_management::SensitiveDirectory;
+use crate::services::shared::infrastructure::{ClockService, SecureDeleteService};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use tracing::{debug, info, warn};
+
+/// Longest TTL a session can be given or extended to, in minutes (one week)
+pub const MAX_SESSION_TTL_MINUTES: u32 = 7 * 24 * 60;
+
+pub struct CleanupSessionService {
+    /// Store location; the config directory's file when `None`
+    store_path: Option<PathBuf>,
+    clock: ClockService,
+}
+
+impl Default for CleanupSessionService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CleanupSessionService {
+    pub fn new() -> Self {
+        Self {
+            store_path: None,
+            clock: ClockService::global().clone(),
+        }
+    }
+
+    /// Service over the store at `store_path`
+    pub fn at(store_path: PathBuf, clock: ClockService) -> Self {
+        Self {
+            store_path: Some(store_path),
+            clock,
+        }
+    }
+
+    /// Schedule the files of an extraction for deletion in `ttl_minutes`
+    pub fn register(
+        &self,
+        output_dir: &Path,
+        files: &[FileInfo],
+        ttl_minutes: u32,
+    ) -> CryptoResult<CleanupSessionSummary> {
+        let output_dir = output_dir.canonicalize().map_err(|e| {
+            CryptoError::DirectoryNotFound(format!("{}: {e}", output_dir.display()))
+        })?;
+        // Plaintext until the session runs; keep it out of the search index
+        SensitiveDirectory::protect(&output_dir);
+
+        let files = files
+            .iter()
+            .filter_map(|file| {
+                let relative = file.path.strip_prefix(&output_dir).ok().or_else(|| {
+                    // Extraction reports paths under the directory as given
+                    let canonical = file.path.canonicalize().ok()?;
+                    let relative = canonical.strip_prefix(&output_dir).ok()?;
+                    file.path.ends_with(relative).then_some(relative)
+                })?;
+                Some(CleanupFile {
+                    path: relative_key(relative),
+                    size: file.size,
+                    sha256: file.hash.clone(),
+                })
+            })
+            .collect();
+
+        let now = self.clock.now();
+        let session = CleanupSession {
+            id: uuid::Uuid::new_v4().to_string(),
+            output_dir: output_dir.to_string_lossy().into_owned(),
+            created_at: now,
+            deadline: now + minutes(ttl_minutes),
+            files,
+        };
+        let summary = CleanupSessionSummary::from(&session);
+
+        let mut store = self.load()?;
+        store.sessions.push(session);
+        self.save(&store)?;
+
+        info!(
+            session_id = %summary.id,
+            deadline = %summary.deadline,
+            file_count = summary.file_count,
+            "Scheduled cleanup of decrypted output"
+        );
+        Ok(summary)
+    }
+
+    /// Pending cleanups, soonest first
+    pub fn list(&self) -> CryptoResult<Vec<CleanupSessionSummary>> {
+        let mut sessions: Vec<CleanupSessionSummary> = self
+            .load()?
+            .sessions
+            .iter()
+            .map(CleanupSessionSummary::from)
+            .collect();
+        sessions.sort_by_key(|s| s.deadline);
+        Ok(sessions)
+    }
+
+    /// Push a session's deadline back by `extra_minutes`
+    ///
+    /// An overdue session is extended from now.
+    pub fn extend(&self, id: &str, extra_minutes: u32) -> CryptoResult<CleanupSessionSummary> {
+        let now = self.clock.now();
+        let mut store = self.load()?;
+        let session = store.get_mut(id).ok_or_else(|| not_found(id))?;
+        session.deadline = session.deadline.max(now) + minutes(extra_minutes);
+        let summary = CleanupSessionSummary::from(&*session);
+        self.save(&store)?;
+
+        debug!(session_id = %id, deadline = %summary.deadline, "Extended cleanup session");
+        Ok(summary)
+    }
+
+    /// Keep the extracted files; the session is forgotten
+    pub fn cancel(&self, id: &str) -> CryptoResult<()> {
+        let mut store = self.load()?;
+        store.remove(id).ok_or_else(|| not_found(id))?;
+        self.save(&store)?;
+
+        info!(session_id = %id, "Cancelled cleanup session");
+        Ok(())
+    }
+
+    /// Clean up every session past its deadline
+    ///
+    /// Due sessions leave the store before anything is deleted, so an
+    /// interrupted cleanup never runs twice. Nothing runs while the clock
+    /// looks wrong.
+    pub fn run_due(&self) -> CryptoResult<Vec<CleanupResult>> {
+        if !self.clock.is_reliable() {
+            warn!("System clock looks wrong, postponing scheduled cleanups");
+            return Ok(Vec::new());
+        }
+
+        let now = self.clock.now();
+        self.run_matching(|s| s.deadline <= now)
+    }
+
+    /// Clean up every pending session now, whatever its deadline
+    ///
+    /// For panic lock; the clock isn't consulted.
+    pub fn run_all(&self) -> CryptoResult<Vec<CleanupResult>> {
+        self.run_matching(|_| true)
+    }
+
+    /// Clean up one pending session now
+    pub fn run_now(&self, id: &str) -> CryptoResult<CleanupResult> {
+        self.run_matching(|s| s.id == id)?
+            .pop()
+            .ok_or_else(|| not_found(id))
+    }
+
+    fn run_matching(
+        &self,
+        is_due: impl Fn(&CleanupSession) -> bool,
+    ) -> CryptoResult<Vec<CleanupResult>> {
+        let mut store = self.load()?;
+        let (due, pending): (Vec<_>, Vec<_>) = store.sessions.drain(..).partition(is_due);
+        if due.is_empty() {
+            return Ok(Vec::new());
+        }
+        store.sessions = pending;
+        self.save(&store)?;
+
+        Ok(due.iter().map(clean_session).collect())
+    }
+
+    fn store_path(&self) -> CryptoResult<PathBuf> {
+        match &self.store_path {
+            Some(path) => Ok(path.clone()),
+            None => CleanupSessionStore::get_store_path()
+                .map_err(|e| CryptoError::ConfigurationError(e.to_string())),
+        }
+    }
+
+    fn load(&self) -> CryptoResult<CleanupSessionStore> {
+        CleanupSessionStore::load_from(&self.store_path()?)
+            .map_err(|e| CryptoError::IoError(format!("Failed to load cleanup sessions: {e}")))
+    }
+
+    fn save(&self, store: &CleanupSessionStore) -> CryptoResult<()> {
+        store
+            .save_to(&self.store_path()?)
+            .map_err(|e| CryptoError::IoError(format!("Failed to save cleanup sessions: {e}")))
+    }
+}
+
+fn minutes(count: u32) -> chrono::Duration {
+    chrono::Duration::minutes(i64::from(count))
+}
+
+fn not_found(id: &str) -> CryptoError {
+    CryptoError::InvalidInput(format!("No pending cleanup session '{id}'"))
+}
+
+/// `/`-separated form of a path relative to the output directory
+fn relative_key(relative: &Path) -> String {
+    relative
+        .iter()
+        .map(|part| part.to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+fn clean_session(session: &CleanupSession) -> CleanupResult {
+    let output_dir = Path::new(&session.output_dir);
+    let outcome = match check_session(session, output_dir) {
+        Some(reason) => {
+            warn!(session_id = %session.id, reason = ?reason, "Refusing scheduled cleanup");
+            CleanupOutcome::Refused { reason }
+        }
+        None => delete_files(session, output_dir),
+    };
+
+    CleanupResult {
+        session_id: session.id.clone(),
+        output_dir: session.output_dir.clone(),
+        outcome,
+    }
+}
+
+/// Why the directory must be left alone, if it must
+fn check_session(session: &CleanupSession, output_dir: &Path) -> Option<CleanupRefusal> {
+    // Canonical at registration; a different answer now means it was moved
+    // or replaced by a link
+    if output_dir.canonicalize().ok().as_deref() != Some(output_dir) {
+        return Some(CleanupRefusal::DirectoryMoved);
+    }
+
+    let extracted: HashMap<&str, &CleanupFile> =
+        session.files.iter().map(|f| (f.path.as_str(), f)).collect();
+    let mut foreign = Vec::new();
+    let mut modified = Vec::new();
+
+    for entry in walkdir::WalkDir::new(output_dir).min_depth(1) {
+        let Ok(entry) = entry else {
+            // Unreadable entries might be anything
+            return Some(CleanupRefusal::ForeignFiles {
+                paths: vec![output_dir.to_string_lossy().into_owned()],
+            });
+        };
+        if entry.file_type().is_dir()
+            || (entry.depth() == 1 && SensitiveDirectory::is_marker(entry.file_name()))
+        {
+            continue;
+        }
+        let Ok(relative) = entry.path().strip_prefix(output_dir) else {
+            continue;
+        };
+        let key = relative_key(relative);
+        match extracted.get(key.as_str()) {
+            Some(file) if entry.file_type().is_file() => {
+                if !matches_extraction(entry.path(), file) {
+                    modified.push(key);
+                }
+            }
+            _ => foreign.push(key),
+        }
+    }
+
+    if !foreign.is_empty() {
+        foreign.sort();
+        return Some(CleanupRefusal::ForeignFiles { paths: foreign });
+    }
+    if !modified.is_empty() {
+        modified.sort();
+        return Some(CleanupRefusal::ModifiedFiles { paths: modified });
+    }
+    None
+}
+
+fn matches_extraction(path: &Path, file: &CleanupFile) -> bool {
+    let size_matches = fs::metadata(path).is_ok_and(|m| m.len() == file.size);
+    size_matches && sha256_file(path).is_ok_and(|hash| hash == file.sha256)
+}
+
+fn sha256_file(path: &Path) -> io::Result<String> {
+    let mut hasher = Sha256::new();
+    io::copy(&mut fs::File::open(path)?, &mut hasher)?;
+    Ok(hex::encode(hasher.finalize()))
+}
+
+fn delete_files(session: &CleanupSession, output_dir: &Path) -> CleanupOutcome {
+    let deleter = SecureDeleteService::new();
+    let mut honesty = deleter.capability(output_dir).honesty;
+    let mut files_removed = 0;
+    let mut failures = Vec::new();
+
+    for file in &session.files {
+        let path = output_dir.join(&file.path);
+        if !path.exists() {
+            continue;
+        }
+        match deleter.delete_file(&path) {
+            Ok(file_honesty) => {
+                files_removed += 1;
+                honesty = honesty.min(file_honesty);
+            }
+            Err(e) => {
+                warn!(path = %path.display(), error = %e, "Failed to delete extracted file");
+                failures.push(file.path.clone());
+            }
+        }
+    }
+    remove_empty_dirs(output_dir);
+
+    info!(
+        session_id = %session.id,
+        files_removed,
+        failures = failures.len(),
+        ?honesty,
+        "Deleted decrypted output"
+    );
+    CleanupOutcome::Deleted {
+        files_removed,
+        failures,
+        honesty,
+    }
+}
+
+/// Remove the directories of the tree that are now empty, deepest first
+///
+/// The indexing markers registration put in `output_dir` go with it.
+fn remove_empty_dirs(output_dir: &Path) {
+    let dirs = walkdir::WalkDir::new(output_dir)
+        .contents_first(true)
+        .into_iter()
+        .filter_map(Result::ok)
+        .filter(|entry| entry.file_type().is_dir());
+    for dir in dirs {
+        if dir.path() == output_dir
+            && let Ok(entries) = fs::read_dir(output_dir)
+        {
+            let entries: Vec<_> = entries.filter_map(Result::ok).collect();
+            if entries
+                .iter()
+                .all(|entry| SensitiveDirectory::is_marker(&entry.file_name()))
+            {
+                entries.iter().for_each(|entry| {
+                    let _ = fs::remove_file(entry.path());
+                });
+            }
+        }
+        // Fails, harmlessly, on directories that still hold something
+        let _ = fs::remove_dir(dir.path());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::shared::infrastructure::clock::testing::{FakeClock, service};
+    use crate::services::shared::infrastructure::io::get_secure_delete_capability;
+    use chrono::Utc;
+    use std::time::Duration;
+    use tempfile::TempDir;
+
+    struct Fixture {
+        temp: TempDir,
+        output_dir: PathBuf,
+        files: Vec<FileInfo>,
+    }
+
+    /// Output directory holding `docs/a.txt` and `b.txt`, as extracted
+    fn extraction() -> Fixture {
+        let temp = TempDir::new().unwrap();
+        let output_dir = temp.path().join("output");
+        fs::create_dir_all(output_dir.join("docs")).unwrap();
+
+        let files = [("docs/a.txt", "secret a"), ("b.txt", "secret b")]
+            .iter()
+            .map(|(path, content)| {
+                let path = output_dir.join(path);
+                fs::write(&path, content).unwrap();
+                FileInfo {
+                    path,
+                    size: content.len() as u64,
+                    modified: Utc::now(),
+                    hash: hex::encode(Sha256::digest(content.as_bytes())),
+                    #[cfg(unix)]
+                    permissions: 0o644,
+                }
+            })
+            .collect();
+
+        Fixture {
+            temp,
+            output_dir,
+            files,
+        }
+    }
+
+    fn service_at(fixture: &Fixture, clock: &FakeClock) -> CleanupSessionService {
+        CleanupSessionService::at(fixture.temp.path().join("sessions.json"), service(clock))
+    }
+
+    #[test]
+    fn test_output_deleted_after_ttl() {
+        let fixture = extraction();
+        let clock = FakeClock::at("2026-03-01T00:00:00Z");
+        let sessions = service_at(&fixture, &clock);
+
+        let session = sessions
+            .register(&fixture.output_dir, &fixture.files, 30)
+            .unwrap();
+        assert_eq!(session.file_count, 2);
+
+        clock.advance(Duration::from_secs(29 * 60));
+        assert!(sessions.run_due().unwrap().is_empty());
+        assert!(fixture.output_dir.join("docs/a.txt").exists());
+
+        clock.advance(Duration::from_secs(60));
+        let results = sessions.run_due().unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(
+            results[0].outcome,
+            CleanupOutcome::Deleted {
+                files_removed: 2,
+                failures: vec![],
+                honesty: get_secure_delete_capability(&fixture.output_dir).honesty,
+            }
+        );
+        assert!(!results[0].is_warning());
+        assert!(!fixture.output_dir.exists());
+        assert!(sessions.list().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_sessions_survive_restart() {
+        let fixture = extraction();
+        let clock = FakeClock::at("2026-03-01T00:00:00Z");
+        let id = service_at(&fixture, &clock)
+            .register(&fixture.output_dir, &fixture.files, 10)
+            .unwrap()
+            .id;
+
+        // A fresh service reads the same store, as after relaunching
+        let restarted = service_at(&fixture, &clock);
+        let extended = restarted.extend(&id, 20).unwrap();
+        assert_eq!(
+            extended.deadline,
+            clock.now() + chrono::Duration::minutes(30)
+        );
+
+        clock.advance(Duration::from_secs(31 * 60));
+        let results = service_at(&fixture, &clock).run_due().unwrap();
+        assert_eq!(results[0].session_id, id);
+        assert!(!fixture.output_dir.join("b.txt").exists());
+
+        // Cancelled sessions never run
+        let id = restarted
+            .register(&fixture.temp.path().canonicalize().unwrap(), &[], 1)
+            .unwrap()
+            .id;
+        restarted.cancel(&id).unwrap();
+        assert!(service_at(&fixture, &clock).list().unwrap().is_empty());
+        assert!(restarted.cancel(&id).is_err());
+    }
+
+    #[test]
+    fn test_refuses_when_user_work_would_be_lost() {
+        let clock = FakeClock::at("2026-03-01T00:00:00Z");
+
+        let fixture = extraction();
+        let sessions = service_at(&fixture, &clock);
+        sessions
+            .register(&fixture.output_dir, &fixture.files, 5)
+            .unwrap();
+        fs::write(fixture.output_dir.join("docs/notes.txt"), "mine").unwrap();
+        clock.advance(Duration::from_secs(5 * 60));
+        let results = sessions.run_due().unwrap();
+        assert_eq!(
+            results[0].outcome,
+            CleanupOutcome::Refused {
+                reason: CleanupRefusal::ForeignFiles {
+                    paths: vec!["docs/notes.txt".to_string()],
+                },
+            }
+        );
+        assert!(results[0].is_warning());
+        assert!(fixture.output_dir.join("docs/a.txt").exists());
+        // Refused sessions are dropped rather than retried
+        assert!(sessions.list().unwrap().is_empty());
+
+        let fixture = extraction();
+        let sessions = service_at(&fixture, &clock);
+        sessions
+            .register(&fixture.output_dir, &fixture.files, 5)
+            .unwrap();
+        fs::write(fixture.output_dir.join("b.txt"), "secret B").unwrap();
+        clock.advance(Duration::from_secs(5 * 60));
+        assert!(matches!(
+            &sessions.run_due().unwrap()[0].outcome,
+            CleanupOutcome::Refused {
+                reason: CleanupRefusal::ModifiedFiles { paths },
+            } if paths == &["b.txt"]
+        ));
+
+        let fixture = extraction();
+        let sessions = service_at(&fixture, &clock);
+        sessions
+            .register(&fixture.output_dir, &fixture.files, 5)
+            .unwrap();
+        let moved = fixture.temp.path().join("moved");
+        fs::rename(&fixture.output_dir, &moved).unwrap();
+        clock.advance(Duration::from_secs(5 * 60));
+        assert_eq!(
+            sessions.run_due().unwrap()[0].outcome,
+            CleanupOutcome::Refused {
+                reason: CleanupRefusal::DirectoryMoved,
+            }
+        );
+        assert!(moved.join("docs/a.txt").exists());
+    }
+
+    #[test]
+    fn test_nothing_runs_on_a_bad_clock() {
+        let fixture = extraction();
+        let clock = FakeClock::at("2026-03-01T00:00:00Z");
+        let sessions = service_at(&fixture, &clock);
+        sessions
+            .register(&fixture.output_dir, &fixture.files, 5)
+            .unwrap();
+
+        clock.set_wall("2040-01-01T00:00:00Z");
+        assert!(sessions.run_due().unwrap().is_empty());
+        assert_eq!(sessions.list().unwrap().len(), 1);
+        assert!(fixture.output_dir.join("b.txt").exists());
+    }
+
+    #[test]
+    fn test_run_all_ignores_deadlines() {
+        let fixture = extraction();
+        let clock = FakeClock::at("2026-03-01T00:00:00Z");
+        let sessions = service_at(&fixture, &clock);
+        sessions
+            .register(&fixture.output_dir, &fixture.files, MAX_SESSION_TTL_MINUTES)
+            .unwrap();
+
+        let results = sessions.run_all().unwrap();
+        assert_eq!(results.len(), 1);
+        assert!(!results[0].is_warning());
+        assert!(!fixture.output_dir.exists());
+        assert!(sessions.run_all().unwrap().is_empty());
+    }
+}
@@ -4,11 +4,13 @@
 //! Extracted from commands/crypto/encryption.rs for proper domain separation.
 
 use crate::constants::*;
+use crate::error::StorageError;
 use crate::prelude::*;
 use crate::services::crypto::domain::{CryptoError, CryptoResult};
 use crate::services::crypto::infrastructure as crypto;
 use crate::services::file::infrastructure::file_operations::ArchiveOperation;
 use crate::services::shared::infrastructure::error::ErrorHandler;
+use crate::services::shared::infrastructure::io::{ArchiveOverwrite, ReplacedFile};
 use crate::services::shared::infrastructure::progress::{ProgressManager, update_global_progress};
 
 #[derive(Debug)]
@@ -19,18 +21,22 @@ pub fn new() -> Self {
         Self
     }
 
-    /// Encrypt archive data using age encryption
+    /// Encrypt archive data to the key and any contacts using age encryption
     pub async fn encrypt_archive_data(
         &self,
         archive_data: &[u8],
-        public_key_str: &str,
+        public_key_strs: &[String],
         progress_manager: &mut ProgressManager,
         operation_id: &str,
     ) -> CryptoResult<Vec<u8>> {
         let error_handler = ErrorHandler::new();
 
-        // Convert public key string to crypto module format
-        let public_key = crypto::PublicKey::from(public_key_str.to_string());
+        // Convert public key strings to crypto module format
+        let public_keys: Vec<crypto::PublicKey> = public_key_strs
+            .iter()
+            .cloned()
+            .map(crypto::PublicKey::from)
+            .collect();
 
         // Update progress for encryption step
         progress_manager.set_progress(PROGRESS_ENCRYPT_ENCRYPTING, "Encrypting data...");
@@ -41,12 +47,13 @@ pub async fn encrypt_archive_data(
             "Starting archive data encryption"
         );
 
-        // Perform the actual encryption
+        // Perform the actual encryption; extra recipients are contacts
+        let result = match public_keys.as_slice() {
+            [public_key] => crypto::encrypt_data(archive_data, public_key),
+            public_keys => crypto::encrypt_data_multi_recipient(archive_data, public_keys),
+        };
         let encrypted_data = error_handler
-            .handle_crypto_operation_error(
-                crypto::encrypt_data(archive_data, &public_key),
-                "encrypt_data",
-            )
+            .handle_crypto_operation_error(result, "encrypt_data")
             .map_err(|e| CryptoError::EncryptionFailed(format!("Encryption failed: {}", e)))?;
 
         debug!(
@@ -60,31 +67,45 @@ pub async fn encrypt_archive_data(
     }
 
     /// Write encrypted data to output file
+    ///
+    /// Staged and verified beside the target before it replaces an existing
+    /// archive, so a failed write leaves the previous archive in place.
     pub async fn write_encrypted_file(
         &self,
         encrypted_data: &[u8],
         archive_operation: &ArchiveOperation,
         progress_manager: &mut ProgressManager,
         operation_id: &str,
-    ) -> CryptoResult<String> {
+    ) -> CryptoResult<ReplacedFile> {
         // Update progress for writing step
         progress_manager.set_progress(PROGRESS_ENCRYPT_WRITING, "Writing encrypted file...");
         self.update_progress(operation_id, progress_manager);
 
         let encrypted_path = archive_operation.archive_path.with_extension("age");
 
-        // Write encrypted data to file
-        std::fs::write(&encrypted_path, encrypted_data).map_err(|e| {
+        let write_failed = |e: StorageError| {
             CryptoError::EncryptionFailed(format!("Failed to write encrypted file: {}", e))
-        })?;
+        };
+        let mut overwrite = ArchiveOverwrite::new();
+        overwrite
+            .stage(&encrypted_path, encrypted_data)
+            .map_err(write_failed)?;
+        let written = overwrite
+            .commit()
+            .map_err(write_failed)?
+            .pop()
+            .ok_or_else(|| {
+                CryptoError::EncryptionFailed("Encrypted file was not written".to_string())
+            })?;
 
         debug!(
-            encrypted_path = %encrypted_path.display(),
-            file_size = encrypted_data.len(),
+            encrypted_path = %written.path.display(),
+            file_size = written.size,
+            replaced_existing = written.replaced_existing,
             "Encrypted file written successfully"
         );
 
-        Ok(encrypted_path.to_string_lossy().to_string())
+        Ok(written)
     }
 
     /// Helper method to update global progress
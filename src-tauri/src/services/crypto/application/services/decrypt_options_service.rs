@@ -0,0 +1,655 @@
+//! Decrypt Options Service
+//!
+//! Answers "with what I have at hand, can I open this archive?" from the
+//! archive header alone. Every recipient the header reveals becomes a row:
+//! the registered key it belongs to, whether it can be used right now, and
+//! if not, why and what would change that.
+//!
+//! Only presence is checked. YubiKeys are listed, never opened, and key
+//! files are located, never read, so nothing asks for a PIN or passphrase.
+//! Recipients are identified as in `KeyRecipientCheckService`: YubiKeys by
+//! stanza tag, X25519 keys through the vault's manifest when it accounts for
+//! every X25519 stanza.
+
+use crate::error::StorageError;
+use crate::prelude::*;
+use crate::services::crypto::domain::{CryptoError, CryptoResult};
+use crate::services::crypto::infrastructure::{
+    AgeHeader, is_x25519_recipient, read_age_header_file, yubikey_recipient_tag,
+};
+use crate::services::key_management::shared::infrastructure::{
+    SystemVolumes, VolumeSource, resolve_key_file,
+};
+use crate::services::key_management::shared::{KeyEntry, KeyLifecycleStatus, KeyRegistryService};
+use crate::services::key_management::yubikey::application::services::DeviceService;
+use crate::services::vault::infrastructure::persistence::metadata::VaultMetadata;
+use serde::Serialize;
+use std::collections::HashSet;
+use std::path::Path;
+
+/// How a row's recipient would open the archive
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, specta::Type)]
+#[serde(rename_all = "snake_case")]
+pub enum DecryptOptionKind {
+    Passphrase,
+    Yubikey,
+    /// The archive itself was encrypted to a passphrase
+    ArchivePassphrase,
+    /// A recipient whose private key isn't on this computer
+    External,
+}
+
+/// Why a recipient can't be used right now
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, specta::Type)]
+#[serde(tag = "reason", rename_all = "snake_case")]
+pub enum UnavailableReason {
+    /// The YubiKey isn't plugged in
+    DeviceNotConnected,
+    /// The drive holding the key file isn't connected
+    KeyMediaNotConnected { volume_label: String },
+    /// The key file isn't where it was kept
+    KeyFileMissing,
+    /// The key's lifecycle status rules it out
+    KeyDeactivated { status: KeyLifecycleStatus },
+    /// A key this computer doesn't know, such as one registered elsewhere
+    NotRegistered,
+    /// A contact's key; only they can open the archive with it
+    NotYourKey,
+}
+
+/// One recipient of the archive
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, specta::Type)]
+pub struct DecryptOption {
+    /// Registry ID, when the recipient is a registered key
+    pub key_id: Option<String>,
+    pub label: String,
+    pub kind: DecryptOptionKind,
+    /// False when the key may be a recipient but the header can't tell
+    pub confirmed_recipient: bool,
+    pub available: bool,
+    pub unavailable_reason: Option<UnavailableReason>,
+    /// What would make the key usable, when it isn't
+    pub action: Option<String>,
+}
+
+/// Every way this archive could be opened, and which work right now
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, specta::Type)]
+pub struct DecryptOptions {
+    pub options: Vec<DecryptOption>,
+    /// Recipients the header doesn't identify, e.g. X25519 keys the vault's
+    /// manifest doesn't list or other plugins' stanzas
+    pub unidentified_recipients: usize,
+    /// At least one option is available; the UI enables Decrypt on this
+    pub can_decrypt_now: bool,
+}
+
+/// Service listing an archive's recipients and whether each is at hand
+#[derive(Debug)]
+pub struct DecryptOptionsService {
+    key_registry_service: KeyRegistryService,
+}
+
+impl DecryptOptionsService {
+    pub fn new() -> Self {
+        Self {
+            key_registry_service: KeyRegistryService::new(),
+        }
+    }
+
+    /// Decrypt options for `encrypted_file`
+    ///
+    /// `manifest` is the vault's local manifest, used to identify the
+    /// anonymous X25519 recipients. `devices` is only asked for the list of
+    /// connected YubiKeys, and only when a registered one is a recipient.
+    #[instrument(skip(self, manifest, devices))]
+    pub async fn options(
+        &self,
+        encrypted_file: &Path,
+        manifest: Option<&VaultMetadata>,
+        devices: Option<&dyn DeviceService>,
+    ) -> CryptoResult<DecryptOptions> {
+        let header = read_age_header_file(encrypted_file)?;
+        let registry = self
+            .key_registry_service
+            .load_registry()
+            .map_err(|e| CryptoError::ConfigurationError(e.to_string()))?;
+        let mut keys: Vec<(&str, &KeyEntry)> = registry
+            .keys
+            .iter()
+            .map(|(key_id, entry)| (key_id.as_str(), entry))
+            .collect();
+        keys.sort_unstable_by_key(|(key_id, _)| *key_id);
+        let manifest_recipients: Option<Vec<&str>> = manifest.map(|manifest| {
+            manifest
+                .encryption
+                .recipients
+                .iter()
+                .map(|r| r.public_key.as_str())
+                .collect()
+        });
+
+        let connected = connected_serials(&header, &keys, devices).await;
+        let options = Self::evaluate(
+            &header,
+            &keys,
+            manifest_recipients.as_deref(),
+            &connected,
+            &SystemVolumes,
+        );
+        debug!(
+            options = options.options.len(),
+            can_decrypt_now = options.can_decrypt_now,
+            "Listed decrypt options"
+        );
+        Ok(options)
+    }
+
+    /// Match `keys` against `header`, given what's connected
+    pub fn evaluate(
+        header: &AgeHeader,
+        keys: &[(&str, &KeyEntry)],
+        manifest_recipients: Option<&[&str]>,
+        connected_serials: &HashSet<String>,
+        volumes: &dyn VolumeSource,
+    ) -> DecryptOptions {
+        let mut options = Vec::new();
+        let mut unidentified_recipients = header
+            .stanzas
+            .iter()
+            .filter(|s| !matches!(s.kind.as_str(), "X25519" | "piv-p256" | "scrypt"))
+            .count();
+        let row = |key_id: &str, entry: &KeyEntry, confirmed: bool| {
+            registered_option(key_id, entry, confirmed, connected_serials, volumes)
+        };
+
+        if header.is_passphrase_encrypted() {
+            options.push(DecryptOption::new(
+                None,
+                "Archive passphrase".to_string(),
+                DecryptOptionKind::ArchivePassphrase,
+                true,
+                None,
+            ));
+        }
+
+        for tag in header.piv_p256_tags() {
+            let registered = keys.iter().find(|(_, entry)| match entry {
+                KeyEntry::Yubikey { recipient, .. } => {
+                    yubikey_recipient_tag(recipient).as_deref() == Some(tag)
+                }
+                _ => false,
+            });
+            options.push(match registered {
+                Some((key_id, entry)) => row(key_id, entry, true),
+                None => DecryptOption::new(
+                    None,
+                    "Unregistered YubiKey".to_string(),
+                    DecryptOptionKind::Yubikey,
+                    true,
+                    Some(UnavailableReason::NotRegistered),
+                ),
+            });
+        }
+
+        let x25519_count = header.x25519_count();
+        let known: Option<HashSet<&str>> = manifest_recipients
+            .map(|recipients| {
+                recipients
+                    .iter()
+                    .copied()
+                    .filter(|r| is_x25519_recipient(r))
+                    .collect::<HashSet<&str>>()
+            })
+            .filter(|known| known.len() == x25519_count);
+        match known {
+            _ if x25519_count == 0 => {}
+            Some(known) => {
+                let mut known: Vec<&str> = known.into_iter().collect();
+                known.sort_unstable();
+                for public_key in known {
+                    let registered = keys.iter().find(|(_, entry)| {
+                        !matches!(entry, KeyEntry::Yubikey { .. })
+                            && entry.public_key() == public_key
+                    });
+                    options.push(match registered {
+                        Some((key_id, entry)) => row(key_id, entry, true),
+                        None => DecryptOption::new(
+                            None,
+                            "Unknown recipient".to_string(),
+                            DecryptOptionKind::External,
+                            true,
+                            Some(UnavailableReason::NotRegistered),
+                        ),
+                    });
+                }
+            }
+            // Any passphrase key might be one of them
+            None => {
+                unidentified_recipients += x25519_count;
+                options.extend(
+                    keys.iter()
+                        .filter(|(_, entry)| matches!(entry, KeyEntry::Passphrase { .. }))
+                        .map(|(key_id, entry)| row(key_id, entry, false)),
+                );
+            }
+        }
+
+        let can_decrypt_now = options.iter().any(|option| option.available);
+        DecryptOptions {
+            options,
+            unidentified_recipients,
+            can_decrypt_now,
+        }
+    }
+}
+
+impl Default for DecryptOptionsService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DecryptOption {
+    fn new(
+        key_id: Option<String>,
+        label: String,
+        kind: DecryptOptionKind,
+        confirmed_recipient: bool,
+        unavailable_reason: Option<UnavailableReason>,
+    ) -> Self {
+        let action = unavailable_reason
+            .as_ref()
+            .and_then(|reason| reason.action(&label));
+        Self {
+            key_id,
+            label,
+            kind,
+            confirmed_recipient,
+            available: unavailable_reason.is_none(),
+            unavailable_reason,
+            action,
+        }
+    }
+}
+
+impl UnavailableReason {
+    /// What the user can do to make the key `label` usable
+    fn action(&self, label: &str) -> Option<String> {
+        match self {
+            Self::DeviceNotConnected => Some(format!("Plug in the YubiKey '{}'", label)),
+            Self::KeyMediaNotConnected { volume_label } => Some(format!(
+                "Connect the drive '{}' holding the key file",
+                volume_label
+            )),
+            Self::KeyFileMissing => Some(format!("Relink the key file of '{}'", label)),
+            Self::KeyDeactivated {
+                status: KeyLifecycleStatus::Suspended,
+            } => Some(format!("Reactivate '{}'", label)),
+            Self::NotRegistered => Some("Import or register this key on this computer".to_string()),
+            Self::KeyDeactivated { .. } | Self::NotYourKey => None,
+        }
+    }
+}
+
+/// Row for a registered key, checking only that it's at hand
+fn registered_option(
+    key_id: &str,
+    entry: &KeyEntry,
+    confirmed: bool,
+    connected_serials: &HashSet<String>,
+    volumes: &dyn VolumeSource,
+) -> DecryptOption {
+    let status = entry.lifecycle_status();
+    let disabled = matches!(
+        status,
+        KeyLifecycleStatus::Suspended
+            | KeyLifecycleStatus::Deactivated
+            | KeyLifecycleStatus::Compromised
+            | KeyLifecycleStatus::Destroyed
+    )
+    .then_some(UnavailableReason::KeyDeactivated { status });
+
+    let (kind, reason) = match entry {
+        KeyEntry::Yubikey { serial, .. } => (
+            DecryptOptionKind::Yubikey,
+            disabled.or_else(|| {
+                (!connected_serials.contains(serial))
+                    .then_some(UnavailableReason::DeviceNotConnected)
+            }),
+        ),
+        KeyEntry::Passphrase {
+            key_filename,
+            key_location,
+            ..
+        } => (
+            DecryptOptionKind::Passphrase,
+            disabled.or_else(|| {
+                match resolve_key_file(key_filename, key_location.as_ref(), volumes) {
+                    Ok(path) if path.is_file() => None,
+                    Err(StorageError::KeyMediaNotPresent { volume_label }) => {
+                        Some(UnavailableReason::KeyMediaNotConnected { volume_label })
+                    }
+                    _ => Some(UnavailableReason::KeyFileMissing),
+                }
+            }),
+        ),
+        KeyEntry::Recipient { .. } | KeyEntry::YubikeyPending { .. } => (
+            DecryptOptionKind::External,
+            Some(UnavailableReason::NotYourKey),
+        ),
+    };
+
+    DecryptOption::new(
+        Some(key_id.to_string()),
+        entry.label().to_string(),
+        kind,
+        confirmed,
+        reason,
+    )
+}
+
+/// Serials of the connected YubiKeys, listed only if one could be used
+async fn connected_serials(
+    header: &AgeHeader,
+    keys: &[(&str, &KeyEntry)],
+    devices: Option<&dyn DeviceService>,
+) -> HashSet<String> {
+    let tags: HashSet<&str> = header.piv_p256_tags().into_iter().collect();
+    let wanted = keys.iter().any(|(_, entry)| match entry {
+        KeyEntry::Yubikey { recipient, .. } => {
+            yubikey_recipient_tag(recipient).is_some_and(|tag| tags.contains(tag.as_str()))
+        }
+        _ => false,
+    });
+    let Some(devices) = devices.filter(|_| wanted) else {
+        return HashSet::new();
+    };
+
+    match devices.list_connected_devices().await {
+        Ok(connected) => connected
+            .iter()
+            .map(|device| device.serial().value().to_string())
+            .collect(),
+        Err(e) => {
+            warn!(error = %e, "Could not list YubiKeys; treating none as connected");
+            HashSet::new()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::crypto::infrastructure::HeaderStanza;
+    use crate::services::key_management::shared::domain::models::key_location::{
+        KeyFileLocation, KeyVolume, MountedVolume,
+    };
+    use crate::services::key_management::yubikey::domain::errors::YubiKeyResult;
+    use crate::services::key_management::yubikey::domain::models::{
+        FormFactor, Interface, Pin, Serial, SmartCardReader, YubiKeyDevice,
+    };
+    use age::x25519::Identity;
+    use async_trait::async_trait;
+    use chrono::Utc;
+    use std::path::PathBuf;
+    use tempfile::TempDir;
+
+    const YUBIKEY_RECIPIENT: &str =
+        "age1yubikey1qgyl9efw5cexsg8ee66jpxglnvfaswhd4zjhntqawagp4zgh064puht4g9l";
+    const SERIAL: &str = "12345678";
+
+    /// YubiKeys plugged in, by serial
+    #[derive(Debug)]
+    struct PluggedIn(Vec<&'static str>);
+
+    #[async_trait]
+    impl DeviceService for PluggedIn {
+        async fn list_connected_devices(&self) -> YubiKeyResult<Vec<YubiKeyDevice>> {
+            Ok(self
+                .0
+                .iter()
+                .map(|serial| {
+                    YubiKeyDevice::from_detected_device(
+                        Serial::new(serial.to_string()).unwrap(),
+                        "YubiKey 5C".to_string(),
+                        FormFactor::UsbC,
+                        vec![Interface::USB],
+                        None,
+                    )
+                })
+                .collect())
+        }
+
+        async fn list_readers(&self) -> YubiKeyResult<Vec<SmartCardReader>> {
+            Ok(vec![])
+        }
+
+        async fn is_device_connected(&self, serial: &Serial) -> YubiKeyResult<bool> {
+            Ok(self.0.iter().any(|plugged| *plugged == serial.value()))
+        }
+
+        async fn validate_pin(&self, _serial: &Serial, _pin: &Pin) -> YubiKeyResult<bool> {
+            panic!("decrypt options must not touch the PIN")
+        }
+
+        async fn has_default_pin(&self, _serial: &Serial) -> YubiKeyResult<bool> {
+            panic!("decrypt options must not touch the PIN")
+        }
+
+        async fn get_pin_retries(&self, _serial: &Serial) -> YubiKeyResult<u8> {
+            panic!("decrypt options must not touch the PIN")
+        }
+
+        async fn get_firmware_version(&self, _serial: &Serial) -> YubiKeyResult<Option<String>> {
+            Ok(None)
+        }
+
+        async fn get_capabilities(&self, _serial: &Serial) -> YubiKeyResult<Vec<String>> {
+            Ok(vec![])
+        }
+    }
+
+    /// No removable drives mounted
+    struct NoVolumes;
+
+    impl VolumeSource for NoVolumes {
+        fn mounted_volumes(&self) -> Vec<MountedVolume> {
+            vec![]
+        }
+    }
+
+    fn passphrase_key(label: &str, public_key: &str, key_file: PathBuf) -> KeyEntry {
+        KeyEntry::Passphrase {
+            label: label.to_string(),
+            created_at: Utc::now(),
+            last_used: None,
+            public_key: public_key.to_string(),
+            key_filename: format!("{label}.agekey.enc"),
+            lifecycle_status: KeyLifecycleStatus::Active,
+            status_history: vec![],
+            vault_associations: vec![],
+            deactivated_at: None,
+            previous_lifecycle_status: None,
+            key_location: Some(KeyFileLocation {
+                volume: None,
+                last_known_path: key_file,
+            }),
+            passphrase_policy: 0,
+        }
+    }
+
+    fn yubikey(status: KeyLifecycleStatus) -> KeyEntry {
+        KeyEntry::Yubikey {
+            label: "Desk YubiKey".to_string(),
+            created_at: Utc::now(),
+            last_used: None,
+            serial: SERIAL.to_string(),
+            slot: 1,
+            piv_slot: 82,
+            recipient: YUBIKEY_RECIPIENT.to_string(),
+            identity_tag: "AGE-PLUGIN-YUBIKEY-1TEST".to_string(),
+            model: "YubiKey 5C".to_string(),
+            firmware_version: None,
+            recovery_code_hash: String::new(),
+            lifecycle_status: status,
+            status_history: vec![],
+            vault_associations: vec![],
+            deactivated_at: None,
+            previous_lifecycle_status: None,
+            last_reader: None,
+        }
+    }
+
+    fn header(x25519: usize, yubikey_tags: &[&str]) -> AgeHeader {
+        let stanza = |kind: &str, arg: &str| HeaderStanza {
+            kind: kind.to_string(),
+            args: vec![arg.to_string()],
+        };
+        let mut stanzas: Vec<HeaderStanza> =
+            (0..x25519).map(|_| stanza("X25519", "c2hhcmU")).collect();
+        stanzas.extend(yubikey_tags.iter().map(|tag| stanza("piv-p256", tag)));
+        AgeHeader { stanzas }
+    }
+
+    fn public_key() -> String {
+        Identity::generate().to_public().to_string()
+    }
+
+    fn reason(options: &DecryptOptions, label: &str) -> Option<UnavailableReason> {
+        options
+            .options
+            .iter()
+            .find(|option| option.label == label)
+            .unwrap_or_else(|| panic!("no option '{label}'"))
+            .unavailable_reason
+            .clone()
+    }
+
+    #[tokio::test]
+    async fn test_yubikey_is_available_only_when_plugged_in() {
+        let tag = yubikey_recipient_tag(YUBIKEY_RECIPIENT).unwrap();
+        let header = header(0, &[&tag]);
+        let key = yubikey(KeyLifecycleStatus::Active);
+        let keys = [("yubikey_1", &key)];
+
+        let unplugged = connected_serials(&header, &keys, Some(&PluggedIn(vec![]))).await;
+        let options = DecryptOptionsService::evaluate(&header, &keys, None, &unplugged, &NoVolumes);
+        assert_eq!(
+            reason(&options, "Desk YubiKey"),
+            Some(UnavailableReason::DeviceNotConnected)
+        );
+        assert!(!options.can_decrypt_now);
+        assert_eq!(
+            options.options[0].action.as_deref(),
+            Some("Plug in the YubiKey 'Desk YubiKey'")
+        );
+
+        let plugged = connected_serials(&header, &keys, Some(&PluggedIn(vec![SERIAL]))).await;
+        let options = DecryptOptionsService::evaluate(&header, &keys, None, &plugged, &NoVolumes);
+        assert!(options.can_decrypt_now);
+        assert_eq!(options.options[0].key_id.as_deref(), Some("yubikey_1"));
+
+        // A deactivated key stays unavailable even when plugged in
+        let key = yubikey(KeyLifecycleStatus::Deactivated);
+        let keys = [("yubikey_1", &key)];
+        let options = DecryptOptionsService::evaluate(&header, &keys, None, &plugged, &NoVolumes);
+        assert_eq!(
+            reason(&options, "Desk YubiKey"),
+            Some(UnavailableReason::KeyDeactivated {
+                status: KeyLifecycleStatus::Deactivated
+            })
+        );
+    }
+
+    #[test]
+    fn test_passphrase_key_needs_its_key_file() {
+        let temp = TempDir::new().unwrap();
+        let key_file = temp.path().join("home.agekey.enc");
+        std::fs::write(&key_file, b"key").unwrap();
+        let (home, travel, usb) = (public_key(), public_key(), public_key());
+
+        let home_key = passphrase_key("Home", &home, key_file);
+        let travel_key = passphrase_key("Travel", &travel, temp.path().join("gone.agekey.enc"));
+        let mut usb_key = passphrase_key("USB", &usb, temp.path().join("missing"));
+        if let KeyEntry::Passphrase { key_location, .. } = &mut usb_key {
+            key_location.as_mut().unwrap().volume = Some(KeyVolume {
+                uuid: None,
+                label: "BACKUP".to_string(),
+                relative_path: PathBuf::from("keys/usb.agekey.enc"),
+            });
+        }
+        let keys = [
+            ("home", &home_key),
+            ("travel", &travel_key),
+            ("usb", &usb_key),
+        ];
+        let recipients = [home.as_str(), travel.as_str(), usb.as_str()];
+
+        let options = DecryptOptionsService::evaluate(
+            &header(3, &[]),
+            &keys,
+            Some(&recipients[..]),
+            &HashSet::new(),
+            &NoVolumes,
+        );
+        assert_eq!(reason(&options, "Home"), None);
+        assert_eq!(
+            reason(&options, "Travel"),
+            Some(UnavailableReason::KeyFileMissing)
+        );
+        assert_eq!(
+            reason(&options, "USB"),
+            Some(UnavailableReason::KeyMediaNotConnected {
+                volume_label: "BACKUP".to_string()
+            })
+        );
+        assert!(options.can_decrypt_now);
+        assert!(
+            options
+                .options
+                .iter()
+                .all(|option| option.confirmed_recipient)
+        );
+        assert_eq!(options.unidentified_recipients, 0);
+    }
+
+    #[test]
+    fn test_unknown_external_recipient() {
+        let temp = TempDir::new().unwrap();
+        let key_file = temp.path().join("home.agekey.enc");
+        std::fs::write(&key_file, b"key").unwrap();
+        let (home, stranger) = (public_key(), public_key());
+        let home_key = passphrase_key("Home", &home, key_file);
+        let keys = [("home", &home_key)];
+
+        // Encrypted to someone else only, plus a YubiKey registered elsewhere
+        let options = DecryptOptionsService::evaluate(
+            &header(1, &["AbCdEf"]),
+            &keys,
+            Some(&[stranger.as_str()][..]),
+            &HashSet::new(),
+            &NoVolumes,
+        );
+        assert_eq!(
+            reason(&options, "Unknown recipient"),
+            Some(UnavailableReason::NotRegistered)
+        );
+        assert_eq!(
+            reason(&options, "Unregistered YubiKey"),
+            Some(UnavailableReason::NotRegistered)
+        );
+        assert!(!options.can_decrypt_now);
+
+        // Without a manifest the header can't say, so the key is only a maybe
+        let options = DecryptOptionsService::evaluate(
+            &header(1, &[]),
+            &keys,
+            None,
+            &HashSet::new(),
+            &NoVolumes,
+        );
+        assert_eq!(options.unidentified_recipients, 1);
+        assert!(!options.options[0].confirmed_recipient);
+        assert!(options.can_decrypt_now);
+    }
+}
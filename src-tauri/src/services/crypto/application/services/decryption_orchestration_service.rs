@@ -4,20 +4,37 @@
 //! This is the main entry point for decryption operations.
 
 use super::{
-    ArchiveExtractionService, KeyRetrievalDecryptionService, ManifestVerificationService,
-    PassphraseDecryptionService, YubiKeyDecryptionService,
+    ArchiveExtractionService, DecryptOptions, DecryptOptionsService, EmbeddedManifestService,
+    KeyRecipientCheck, KeyRecipientCheckService, KeyRetrievalDecryptionService, ManifestResolution,
+    ManifestSource, ManifestVerificationService, PassphraseDecryptionService,
+    SalvageDecryptionService, SalvageReport, ToolIndependenceReport, ToolIndependenceService,
+    YubiKeyDecryptionService, check_app_version,
 };
 use crate::constants::*;
 use crate::prelude::*;
+use crate::services::crypto::domain::models::{OperationPhase, PhaseTimer, TimingBreakdown};
 use crate::services::crypto::domain::{CryptoError, CryptoResult};
+use crate::services::crypto::infrastructure as crypto;
 use crate::services::file::infrastructure::file_operations;
 use crate::services::key_management::shared::KeyEntry;
+use crate::services::key_management::yubikey::application::services::DeviceService;
+use crate::services::shared::infrastructure::io::IoPacer;
 use crate::services::shared::infrastructure::progress::ProgressManager;
-use crate::services::shared::infrastructure::{get_keys_dir, get_vault_manifest_path};
-use crate::services::vault::application::services::VersionComparisonService;
+use crate::services::shared::infrastructure::{
+    SecureDeleteService, get_keys_dir, get_vault_manifest_path,
+};
+use crate::services::vault::application::services::{
+    ArchivePatchService, ManifestDiscrepancy, PatchLayer, VersionComparisonService,
+};
+use crate::services::vault::domain::models::{PatchManifest, contains_secret_marker};
+use crate::services::vault::infrastructure::persistence::ManifestSignatureCheck;
 use crate::services::vault::infrastructure::persistence::metadata::{BundleType, VaultMetadata};
 use age::secrecy::{ExposeSecret, SecretString};
+use sha2::{Digest, Sha256};
 use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Arc;
+use zeroize::Zeroizing;
 
 /// Input for decryption orchestration
 #[derive(Debug)]
@@ -27,6 +44,12 @@ pub struct DecryptionInput<'a> {
     pub passphrase: SecretString,
     pub custom_output_dir: Option<PathBuf>, // Optional custom override
     pub force_overwrite: bool,              // NEW - for user confirmation
+    pub path_limit_strategy: file_operations::PathLimitStrategy,
+    pub ownership_policy: file_operations::OwnershipPolicy,
+    /// Paths or content types to restore (default: everything)
+    pub restore_filter: file_operations::RestoreFilter,
+    /// Send a YubiKey PIN even if only one attempt is left
+    pub accept_last_attempt: bool,
 }
 
 /// Result of decryption orchestration
@@ -37,6 +60,38 @@ pub struct DecryptionOutput {
     pub output_exists: bool, // NEW - conflict detection
     pub manifest_verified: bool,
     pub external_manifest_restored: Option<bool>,
+    /// Entries renamed to fit platform path limits
+    pub renamed_paths: Vec<file_operations::PathMapping>,
+    /// Which manifest described the archive
+    pub manifest_source: ManifestSource,
+    /// Fields where the external manifest disagrees with the embedded one
+    pub manifest_discrepancies: Vec<ManifestDiscrepancy>,
+    /// Signature check of the external manifest, when there is one
+    pub manifest_signature: Option<ManifestSignatureCheck>,
+    /// How faithfully recorded file ownership was restored
+    pub fidelity: file_operations::FidelityReport,
+    /// Files left out by the restore filter
+    pub skipped_by_filter: usize,
+    /// Vault the archive belongs to, when a manifest names it
+    pub vault_id: Option<String>,
+    /// Time and bytes per pipeline phase (empty when nothing was decrypted)
+    pub timing_breakdown: TimingBreakdown,
+    /// Files kept from an interrupted earlier restore into the same directory
+    pub resumed_files: usize,
+    /// Patches laid over the archive
+    pub patches_applied: usize,
+    /// Recorded patches that weren't beside the archive, so weren't applied
+    pub missing_patches: Vec<String>,
+}
+
+/// Result of rewriting the external manifest from an archive
+#[derive(Debug)]
+pub struct RegeneratedManifest {
+    pub manifest_path: PathBuf,
+    pub vault_id: String,
+    /// Fields that differed in the replaced sidecar (empty if none existed)
+    pub replaced_discrepancies: Vec<ManifestDiscrepancy>,
+    pub previous_existed: bool,
 }
 
 /// Main orchestration service for decryption operations
@@ -47,6 +102,10 @@ pub struct DecryptionOrchestrationService {
     yubikey_decryption: YubiKeyDecryptionService,
     archive_extraction: ArchiveExtractionService,
     manifest_verification: ManifestVerificationService,
+    embedded_manifest: EmbeddedManifestService,
+    recipient_check: KeyRecipientCheckService,
+    decrypt_options: DecryptOptionsService,
+    salvage_decryption: SalvageDecryptionService,
 }
 
 impl DecryptionOrchestrationService {
@@ -57,6 +116,10 @@ pub fn new() -> Self {
             yubikey_decryption: YubiKeyDecryptionService::new(),
             archive_extraction: ArchiveExtractionService::new(),
             manifest_verification: ManifestVerificationService::new(),
+            embedded_manifest: EmbeddedManifestService::new(),
+            recipient_check: KeyRecipientCheckService::new(),
+            decrypt_options: DecryptOptionsService::new(),
+            salvage_decryption: SalvageDecryptionService::new(),
         }
     }
 
@@ -74,6 +137,9 @@ pub async fn decrypt(
             "Starting decryption orchestration"
         );
 
+        let mut timer = PhaseTimer::start();
+        let phase = timer.begin(OperationPhase::Validation);
+
         // Extract vault name from encrypted filename
         let vault_name = self.extract_vault_name_from_file(input.encrypted_file)?;
 
@@ -87,29 +153,29 @@ pub async fn decrypt(
         // Check if output already exists (for frontend conflict dialog)
         let output_exists = self.check_output_exists(&output_dir);
 
+        // A journal left by an interrupted restore lets it continue without
+        // the conflict prompt; whether it belongs to this archive is checked
+        // once the archive has been read
+        let interrupted = file_operations::ExtractionJournal::exists(&output_dir);
+
         info!(
             vault_name = %vault_name,
             output_dir = %output_dir.display(),
             output_exists = output_exists,
             force_overwrite = input.force_overwrite,
+            interrupted,
             "Determined output directory for decryption"
         );
 
         // CRITICAL: Stop if output exists and no force flag
-        if output_exists && !input.force_overwrite {
+        if output_exists && !input.force_overwrite && !interrupted {
             info!(
                 output_dir = %output_dir.display(),
                 "Output directory exists and force_overwrite is false - returning conflict response"
             );
 
             // Return early with conflict info - DON'T decrypt yet
-            return Ok(DecryptionOutput {
-                extracted_files: vec![], // Empty - nothing decrypted yet
-                output_dir,
-                output_exists: true, // Signal conflict to frontend
-                manifest_verified: false,
-                external_manifest_restored: None,
-            });
+            return Ok(Self::conflict_output(output_dir));
         }
 
         // Step 1: Load key from registry
@@ -123,17 +189,31 @@ pub async fn decrypt(
             "Retrieved key entry from registry"
         );
 
+        // Refuse a key that isn't a recipient before its passphrase is used
+        self.ensure_key_is_recipient(input.encrypted_file, input.key_id)?;
+        timer.end(phase, 0);
+
         // Step 2: Read encrypted file
         progress_manager.set_progress(PROGRESS_DECRYPT_READ_FILE, "Reading encrypted file...");
 
-        let encrypted_data = std::fs::read(input.encrypted_file).map_err(|e| {
-            error!(
-                encrypted_file = %input.encrypted_file,
-                error = %e,
-                "Failed to read encrypted file"
-            );
-            CryptoError::InvalidInput(format!("Failed to read encrypted file: {}", e))
-        })?;
+        let phase = timer.begin(OperationPhase::Read);
+
+        let encrypted_data =
+            crypto::read_age_archive(Path::new(input.encrypted_file)).map_err(|e| {
+                error!(
+                    encrypted_file = %input.encrypted_file,
+                    error = %e,
+                    "Failed to read encrypted file"
+                );
+                CryptoError::InvalidInput(format!("Failed to read encrypted file: {}", e))
+            })?;
+
+        let archive_sha256 = hex::encode(Sha256::digest(&encrypted_data));
+        let policy = file_operations::ExtractionPolicy {
+            path_limit_strategy: input.path_limit_strategy,
+            restore_filter: input.restore_filter.clone(),
+        };
+        timer.end(phase, encrypted_data.len() as u64);
 
         debug!(
             encrypted_file = %input.encrypted_file,
@@ -141,75 +221,143 @@ pub async fn decrypt(
             "Successfully read encrypted file"
         );
 
+        // The journal was left by another archive or policy: the directory
+        // holds something else, so the usual conflict applies
+        if interrupted
+            && output_exists
+            && !input.force_overwrite
+            && !file_operations::ExtractionJournal::matches(&output_dir, &archive_sha256, &policy)
+        {
+            info!(
+                output_dir = %output_dir.display(),
+                "Restore journal belongs to another archive - returning conflict response"
+            );
+            return Ok(Self::conflict_output(output_dir));
+        }
+
         // Step 3: Decrypt based on key type
         progress_manager.set_progress(PROGRESS_DECRYPT_DECRYPTING, "Decrypting data...");
 
-        let decrypted_data = match &key_entry {
-            KeyEntry::Passphrase { key_filename, .. } => {
-                debug!(
-                    key_id = %input.key_id,
-                    key_filename = %key_filename,
-                    "Using passphrase-based decryption"
-                );
-
-                progress_manager
-                    .set_progress(PROGRESS_DECRYPT_KEY_DECRYPT, "Decrypting private key...");
-
-                self.passphrase_decryption.decrypt_with_passphrase(
-                    &encrypted_data,
-                    key_filename,
-                    input.passphrase,
-                )?
-            }
-            KeyEntry::Yubikey { .. } => {
-                debug!(
-                    key_id = %input.key_id,
-                    "Using YubiKey-based decryption"
-                );
-
-                // Convert SecretString to &str safely
-                let passphrase_str =
-                    String::from_utf8_lossy(input.passphrase.expose_secret().as_bytes());
+        if matches!(key_entry, KeyEntry::Passphrase { .. }) {
+            progress_manager
+                .set_progress(PROGRESS_DECRYPT_KEY_DECRYPT, "Decrypting private key...");
+        }
 
-                self.yubikey_decryption.decrypt_with_yubikey(
-                    &encrypted_data,
-                    &key_entry,
-                    &passphrase_str,
-                )?
-            }
-            KeyEntry::Recipient { .. } => {
-                error!(
-                    key_id = %input.key_id,
-                    "Cannot decrypt with recipient key - no private key available"
-                );
-                return Err(CryptoError::DecryptionFailed(
-                    "Cannot decrypt with a recipient key. Recipients are public keys only - you need the owner's private key to decrypt.".to_string()
-                ));
-            }
-        };
+        let phase = timer.begin(OperationPhase::Decryption);
+        // Kept to decrypt the archive's patches, if it has any
+        let patch_passphrase = input.passphrase.clone();
+        let decrypted_data = self.decrypt_payload(
+            &encrypted_data,
+            input.key_id,
+            &key_entry,
+            input.passphrase,
+            input.accept_last_attempt,
+        )?;
 
         debug!(
             decrypted_data_size = decrypted_data.len(),
             "Successfully decrypted data"
         );
 
+        // Refuse archives from a newer app before extracting anything
+        let embedded = self.embedded_manifest.read_embedded(&decrypted_data)?;
+        if let Some(manifest) = &embedded {
+            check_app_version(manifest)?;
+        }
+        timer.end(phase, encrypted_data.len() as u64);
+
         // Step 4: Extract archive
         progress_manager.set_progress(PROGRESS_DECRYPT_EXTRACT, "Extracting archive...");
 
-        let extracted_files = self
-            .archive_extraction
-            .extract_archive(&decrypted_data, &output_dir)?;
+        // A filtered restore leaves directories out on purpose
+        let restore_filtered = !input.restore_filter.is_empty();
+        let phase = timer.begin(OperationPhase::Extraction);
+        // Resuming is best effort; without a journal everything is extracted
+        let journal =
+            file_operations::ExtractionJournal::open(&output_dir, &archive_sha256, &policy)
+                .inspect_err(|e| warn!(error = %e, "Failed to open restore journal"))
+                .ok()
+                .map(Arc::new);
+        let extraction = self.archive_extraction.extract_archive_filtered(
+            &decrypted_data,
+            &output_dir,
+            input.path_limit_strategy,
+            input.restore_filter,
+            Some(Arc::new(IoPacer::new(progress_manager.io_priority()))),
+            journal.clone(),
+            embedded
+                .as_ref()
+                .and_then(|manifest| manifest.encryption.safety_limits),
+        )?;
+        if let Some(journal) = journal {
+            journal.finish();
+        }
+        let mut extracted_files = extraction.files;
+        // Names that aren't valid Unicode were archived escaped
+        if let Some(manifest) = &embedded {
+            file_operations::restore_true_names(
+                &output_dir,
+                &manifest.lossy_archived_paths(),
+                &mut extracted_files,
+            )
+            .map_err(|e| {
+                CryptoError::DecryptionFailed(format!("Failed to restore file names: {}", e))
+            })?;
+        }
+
+        // Lay the archive's patches over it; the newest patch naming a file wins
+        let (patches, missing_patches) = match &embedded {
+            Some(manifest) => self.decrypt_patches(
+                manifest,
+                input.encrypted_file,
+                input.key_id,
+                &key_entry,
+                patch_passphrase,
+                input.accept_last_attempt,
+            )?,
+            None => (Vec::new(), Vec::new()),
+        };
+        if let Some(manifest) = embedded.as_ref().filter(|_| !patches.is_empty()) {
+            let written = ArchivePatchService::apply_layers(
+                manifest,
+                &patches,
+                &output_dir,
+                &policy.restore_filter,
+            )
+            .map_err(|e| CryptoError::DecryptionFailed(format!("Failed to apply patch: {}", e)))?;
+            for (archived, size, sha256) in written {
+                let path = output_dir.join(archived);
+                match extracted_files.iter_mut().find(|file| file.path == path) {
+                    Some(file) => {
+                        file.size = size;
+                        file.hash = sha256;
+                    }
+                    None => warn!(path = %path.display(), "Patched file wasn't extracted"),
+                }
+            }
+            info!(patch_count = patches.len(), "Applied archive patches");
+        }
+        let patch_manifests: Vec<PatchManifest> =
+            patches.into_iter().map(|layer| layer.manifest).collect();
+        timer.end(phase, decrypted_data.len() as u64);
 
         info!(
             extracted_files_count = extracted_files.len(),
+            renamed_count = extraction.renamed_paths.len(),
+            resumed_count = extraction.resumed,
             "Successfully extracted archive"
         );
 
-        // Step 5: Process vault manifest from extracted files
+        // Step 5: Process vault manifest embedded in the archive
         progress_manager.set_progress(PROGRESS_DECRYPT_CLEANUP, "Processing vault manifest...");
 
-        let (manifest_updated, encryption_revision, bundle_manifest) =
-            self.process_vault_manifest(&extracted_files, &output_dir)?;
+        let phase = timer.begin(OperationPhase::Finalize);
+        let (manifest_updated, encryption_revision, resolution) =
+            self.process_vault_manifest(embedded, &vault_name)?;
+        let bundle_manifest = resolution.embedded.clone();
+
+        let fidelity =
+            self.restore_file_ownership(&extracted_files, &resolution, input.ownership_policy);
 
         // Detect if this is a shared bundle (defense-in-depth)
         // Shared bundle = explicit bundle_type OR decrypting with PublicKeyOnly recipient key
@@ -246,19 +394,41 @@ pub async fn decrypt(
                 "Restored keys to registry from vault manifest"
             );
         }
+        timer.end(phase, 0);
 
         // Step 8: Verify manifest if exists
         progress_manager.set_progress(PROGRESS_DECRYPT_VERIFY, "Verifying manifest...");
 
+        let phase = timer.begin(OperationPhase::Verification);
         let manifest_verified = self
             .manifest_verification
-            .verify_manifest(&extracted_files, &output_dir);
+            .verify_manifest(&extracted_files, &output_dir)
+            && (restore_filtered
+                || bundle_manifest.as_ref().is_none_or(|manifest| {
+                    self.manifest_verification
+                        .verify_directories(manifest, &output_dir)
+                }))
+            && (patch_manifests.is_empty()
+                || bundle_manifest.as_ref().is_none_or(|manifest| {
+                    ArchivePatchService::verify_layers(
+                        manifest,
+                        &patch_manifests,
+                        &output_dir,
+                        &policy.restore_filter,
+                    )
+                    .inspect_err(|e| warn!(error = %e, "Patched archive verification failed"))
+                    .is_ok()
+                }));
+        timer.end(phase, extracted_files.iter().map(|file| file.size).sum());
 
         // Step 9: Clean up internal files for shared bundles (user sees only their files)
         if is_shared_bundle {
             self.clean_internal_files(&extracted_files, &output_dir)?;
         }
 
+        let timing_breakdown = timer.finish();
+        timing_breakdown.log("decryption");
+
         info!(
             manifest_verified = manifest_verified,
             manifest_updated = manifest_updated,
@@ -278,42 +448,429 @@ pub async fn decrypt(
             } else {
                 Some(manifest_updated)
             },
+            renamed_paths: extraction.renamed_paths,
+            vault_id: resolution.preferred().map(|m| m.vault_id().to_string()),
+            manifest_source: resolution.source,
+            manifest_discrepancies: resolution.discrepancies,
+            manifest_signature: resolution.signature,
+            fidelity,
+            skipped_by_filter: extraction.skipped_by_filter,
+            timing_breakdown,
+            resumed_files: extraction.resumed,
+            patches_applied: patch_manifests.len(),
+            missing_patches,
         })
     }
 
-    /// Process vault manifest from extracted files
+    /// Output signalling that the directory exists, with nothing decrypted
+    fn conflict_output(output_dir: PathBuf) -> DecryptionOutput {
+        DecryptionOutput {
+            extracted_files: vec![], // Empty - nothing decrypted yet
+            output_dir,
+            output_exists: true, // Signal conflict to frontend
+            manifest_verified: false,
+            external_manifest_restored: None,
+            renamed_paths: vec![],
+            manifest_source: ManifestSource::None,
+            manifest_discrepancies: vec![],
+            manifest_signature: None,
+            fidelity: file_operations::FidelityReport::default(),
+            skipped_by_filter: 0,
+            vault_id: None,
+            timing_breakdown: TimingBreakdown::default(),
+            resumed_files: 0,
+            patches_applied: 0,
+            missing_patches: vec![],
+        }
+    }
+
+    /// Decrypt an archive just far enough to read its manifest
     ///
-    /// Reads manifest from bundle, compares with local, and handles version conflicts.
+    /// Nothing is extracted to disk. The embedded manifest is preferred; the
+    /// external manifest is only used for legacy archives without one.
+    #[instrument(skip(self, passphrase))]
+    pub fn read_archive_manifest(
+        &self,
+        encrypted_file: &str,
+        key_id: &str,
+        passphrase: SecretString,
+    ) -> CryptoResult<ManifestResolution> {
+        let vault_name = self.extract_vault_name_from_file(encrypted_file)?;
+        let decrypted_data = self.decrypt_file(encrypted_file, key_id, passphrase)?;
+        let embedded = self.embedded_manifest.read_embedded(&decrypted_data)?;
+        let resolution = self.embedded_manifest.resolve(embedded, &vault_name);
+        resolution.check_app_version()?;
+
+        Ok(resolution)
+    }
+
+    /// Recover what survives of a damaged archive
     ///
-    /// # Returns
-    /// (manifest_was_updated, encryption_revision, bundle_manifest)
-    fn process_vault_manifest(
+    /// Chunks that fail authentication are skipped instead of aborting.
+    /// Without `output_dir` nothing is written and the report predicts the
+    /// outcome. The local manifest names the expected files when the embedded
+    /// one was lost.
+    #[instrument(skip(self, passphrase))]
+    pub fn salvage_archive(
         &self,
-        extracted_files: &[file_operations::FileInfo],
-        output_dir: &Path,
-    ) -> CryptoResult<(bool, Option<u32>, Option<VaultMetadata>)> {
-        // Look for vault manifest in extracted files (e.g., "Vault-001.manifest" or "*.manifest")
-        let manifest_file = extracted_files.iter().find(|file| {
-            file.path.extension().is_some_and(|ext| ext == "manifest")
-                && file.path.file_name().is_some_and(|name| {
-                    name.to_string_lossy().ends_with(".manifest")
-                        && !name.to_string_lossy().contains("vault.manifest")
-                })
-        });
+        encrypted_file: &str,
+        key_id: &str,
+        passphrase: SecretString,
+        output_dir: Option<&Path>,
+    ) -> CryptoResult<SalvageReport> {
+        let key_entry = self.key_retrieval.get_decryption_key_info(key_id)?;
+        if !matches!(key_entry, KeyEntry::Passphrase { .. }) {
+            return Err(CryptoError::InvalidInput(
+                "Salvage needs a passphrase key; a YubiKey can't release the private key it needs"
+                    .to_string(),
+            ));
+        }
+        self.ensure_key_is_recipient(encrypted_file, key_id)?;
 
-        let Some(manifest_info) = manifest_file else {
-            info!("No vault manifest found in bundle, skipping version comparison");
-            return Ok((false, None, None));
-        };
+        let encrypted_data = crypto::read_age_archive(Path::new(encrypted_file)).map_err(|e| {
+            CryptoError::InvalidInput(format!("Failed to read encrypted file: {}", e))
+        })?;
+        let private_key = self
+            .passphrase_decryption
+            .unlock_registered_key(key_id, &key_entry, passphrase)?;
+        let fallback_manifest = self
+            .extract_vault_name_from_file(encrypted_file)
+            .ok()
+            .and_then(|vault_name| self.embedded_manifest.load_external(&vault_name));
+
+        self.salvage_decryption.salvage(
+            &encrypted_data,
+            &private_key,
+            output_dir,
+            fallback_manifest.as_ref(),
+        )
+    }
+
+    /// Check an archive decrypts and unpacks with standard age and tar
+    ///
+    /// The key is unlocked here, then only its raw identity is used. Like
+    /// salvage, this needs a passphrase key.
+    #[instrument(skip(self, passphrase))]
+    pub fn verify_tool_independence(
+        &self,
+        archive_path: &Path,
+        key_id: &str,
+        passphrase: SecretString,
+    ) -> CryptoResult<ToolIndependenceReport> {
+        let key_entry = self.key_retrieval.get_decryption_key_info(key_id)?;
+        if !matches!(key_entry, KeyEntry::Passphrase { .. }) {
+            return Err(CryptoError::InvalidInput(
+                "Checking with standard tools needs a passphrase key; a YubiKey can't release its identity"
+                    .to_string(),
+            ));
+        }
+
+        let private_key = self
+            .passphrase_decryption
+            .unlock_registered_key(key_id, &key_entry, passphrase)?;
+        let identity = age::x25519::Identity::from_str(private_key.expose_secret())
+            .map_err(|e| CryptoError::InvalidKey(e.to_string()))?;
+
+        ToolIndependenceService::new().check(archive_path, &identity)
+    }
+
+    /// Decrypt an archive into memory along with its resolved manifest
+    ///
+    /// Nothing is extracted to disk. The plaintext is zeroized when the
+    /// caller drops it.
+    #[instrument(skip(self, passphrase))]
+    pub fn decrypt_in_memory(
+        &self,
+        encrypted_file: &str,
+        key_id: &str,
+        passphrase: SecretString,
+    ) -> CryptoResult<(Zeroizing<Vec<u8>>, ManifestResolution)> {
+        let vault_name = self.extract_vault_name_from_file(encrypted_file)?;
+        let decrypted_data =
+            Zeroizing::new(self.decrypt_file(encrypted_file, key_id, passphrase)?);
+        let embedded = self.embedded_manifest.read_embedded(&decrypted_data)?;
+        let resolution = self.embedded_manifest.resolve(embedded, &vault_name);
+        resolution.check_app_version()?;
+
+        Ok((decrypted_data, resolution))
+    }
 
-        // Read manifest from extracted files
-        let manifest_path = output_dir.join(&manifest_info.path);
-        let content = std::fs::read_to_string(&manifest_path).map_err(|e| {
-            CryptoError::InvalidInput(format!("Failed to read manifest from bundle: {}", e))
+    /// Read an archive's manifest together with previews of its files
+    ///
+    /// The archive is decrypted in memory and previews are built while
+    /// streaming through it; text that looks like key material is not
+    /// previewed.
+    #[instrument(skip(self, passphrase))]
+    pub fn read_archive_previews(
+        &self,
+        encrypted_file: &str,
+        key_id: &str,
+        passphrase: SecretString,
+        limits: &file_operations::PreviewLimits,
+    ) -> CryptoResult<(ManifestResolution, Vec<file_operations::EntryPreview>)> {
+        let (decrypted_data, resolution) =
+            self.decrypt_in_memory(encrypted_file, key_id, passphrase)?;
+        let previews =
+            file_operations::preview_entries(&decrypted_data, limits, contains_secret_marker)
+                .map_err(|e| CryptoError::InvalidInput(format!("Failed to read archive: {e}")))?;
+
+        Ok((resolution, previews))
+    }
+
+    /// Rewrite the external manifest from the copy embedded in an archive
+    #[instrument(skip(self, passphrase))]
+    pub fn regenerate_external_manifest(
+        &self,
+        encrypted_file: &str,
+        key_id: &str,
+        passphrase: SecretString,
+    ) -> CryptoResult<RegeneratedManifest> {
+        let decrypted_data = self.decrypt_file(encrypted_file, key_id, passphrase)?;
+
+        let embedded = self
+            .embedded_manifest
+            .read_embedded(&decrypted_data)?
+            .ok_or_else(|| {
+                CryptoError::InvalidInput(
+                    "Archive has no embedded manifest (shared bundle or created by an older version)"
+                        .to_string(),
+                )
+            })?;
+
+        let previous = self
+            .embedded_manifest
+            .load_external(&embedded.vault.sanitized_name);
+        let replaced_discrepancies = previous
+            .as_ref()
+            .map(|external| VersionComparisonService::find_discrepancies(&embedded, external))
+            .unwrap_or_default();
+
+        let manifest_path = self.embedded_manifest.write_external(&embedded)?;
+
+        Ok(RegeneratedManifest {
+            manifest_path,
+            vault_id: embedded.vault_id().to_string(),
+            replaced_discrepancies,
+            previous_existed: previous.is_some(),
+        })
+    }
+
+    /// Read an encrypted file and decrypt it with a registry key
+    fn decrypt_file(
+        &self,
+        encrypted_file: &str,
+        key_id: &str,
+        passphrase: SecretString,
+    ) -> CryptoResult<Vec<u8>> {
+        let key_entry = self.key_retrieval.get_decryption_key_info(key_id)?;
+        self.ensure_key_is_recipient(encrypted_file, key_id)?;
+
+        let encrypted_data = crypto::read_age_archive(Path::new(encrypted_file)).map_err(|e| {
+            CryptoError::InvalidInput(format!("Failed to read encrypted file: {}", e))
         })?;
 
-        let bundle_manifest: VaultMetadata = serde_json::from_str(&content)
-            .map_err(|e| CryptoError::InvalidInput(format!("Failed to parse manifest: {}", e)))?;
+        // Previews and checks never spend a YubiKey's last PIN attempt
+        self.decrypt_payload(&encrypted_data, key_id, &key_entry, passphrase, false)
+    }
+
+    /// Check from the archive header whether `key_id` can open it
+    ///
+    /// Reads only the header; the vault's local manifest (if any) identifies
+    /// the X25519 recipients.
+    #[instrument(skip(self))]
+    pub fn check_key_recipient(
+        &self,
+        encrypted_file: &str,
+        key_id: &str,
+    ) -> CryptoResult<KeyRecipientCheck> {
+        let manifest = self
+            .extract_vault_name_from_file(encrypted_file)
+            .ok()
+            .and_then(|vault_name| self.embedded_manifest.load_external(&vault_name));
+        self.recipient_check
+            .check(Path::new(encrypted_file), key_id, manifest.as_ref())
+    }
+
+    /// List the archive's recipients and which of them can open it right now
+    ///
+    /// Like `check_key_recipient`, reads only the header and the vault's
+    /// local manifest. YubiKeys are listed through `devices`, never opened.
+    #[instrument(skip(self, devices))]
+    pub async fn get_decrypt_options(
+        &self,
+        encrypted_file: &str,
+        devices: Option<&dyn DeviceService>,
+    ) -> CryptoResult<DecryptOptions> {
+        let manifest = self
+            .extract_vault_name_from_file(encrypted_file)
+            .ok()
+            .and_then(|vault_name| self.embedded_manifest.load_external(&vault_name));
+        self.decrypt_options
+            .options(Path::new(encrypted_file), manifest.as_ref(), devices)
+            .await
+    }
+
+    /// Fail with `KeyNotARecipient` when the header rules the key out
+    ///
+    /// An unreadable header is left for the decryption itself to report.
+    fn ensure_key_is_recipient(&self, encrypted_file: &str, key_id: &str) -> CryptoResult<()> {
+        match self.check_key_recipient(encrypted_file, key_id) {
+            Ok(KeyRecipientCheck::NotARecipient {
+                matching_key_labels,
+            }) => {
+                warn!(
+                    key_id = %key_id,
+                    matching_keys = matching_key_labels.len(),
+                    "Selected key is not a recipient of the archive"
+                );
+                Err(CryptoError::KeyNotARecipient {
+                    matching_key_labels,
+                })
+            }
+            Ok(_) => Ok(()),
+            Err(e) => {
+                debug!(error = %e, "Could not check archive recipients; decrypting anyway");
+                Ok(())
+            }
+        }
+    }
+
+    /// Decrypt archive bytes based on key type
+    fn decrypt_payload(
+        &self,
+        encrypted_data: &[u8],
+        key_id: &str,
+        key_entry: &KeyEntry,
+        passphrase: SecretString,
+        accept_last_attempt: bool,
+    ) -> CryptoResult<Vec<u8>> {
+        match key_entry {
+            KeyEntry::Passphrase { key_filename, .. } => {
+                debug!(
+                    key_id = %key_id,
+                    key_filename = %key_filename,
+                    "Using passphrase-based decryption"
+                );
+
+                self.passphrase_decryption.decrypt_with_registered_key(
+                    encrypted_data,
+                    key_id,
+                    key_entry,
+                    passphrase,
+                )
+            }
+            KeyEntry::Yubikey { .. } => {
+                debug!(
+                    key_id = %key_id,
+                    "Using YubiKey-based decryption"
+                );
+
+                // Convert SecretString to &str safely
+                let passphrase_str = String::from_utf8_lossy(passphrase.expose_secret().as_bytes());
+
+                self.yubikey_decryption.decrypt_with_yubikey(
+                    encrypted_data,
+                    key_entry,
+                    &passphrase_str,
+                    accept_last_attempt,
+                )
+            }
+            KeyEntry::Recipient { .. } => {
+                error!(
+                    key_id = %key_id,
+                    "Cannot decrypt with recipient key - no private key available"
+                );
+                Err(CryptoError::DecryptionFailed(
+                    "Cannot decrypt with a recipient key. Recipients are public keys only - you need the owner's private key to decrypt.".to_string()
+                ))
+            }
+            KeyEntry::YubikeyPending { .. } => Err(CryptoError::DecryptionFailed(
+                "Cannot decrypt with a reserved YubiKey slot. Register the YubiKey for it first."
+                    .to_string(),
+            )),
+        }
+    }
+
+    /// Decrypt the patches recorded for the archive, oldest first
+    ///
+    /// Patches are encrypted to the archive's recipients, so the same key
+    /// opens them; a YubiKey is asked once per patch. Also returns the names
+    /// of recorded patches missing from beside the archive.
+    fn decrypt_patches(
+        &self,
+        manifest: &VaultMetadata,
+        encrypted_file: &str,
+        key_id: &str,
+        key_entry: &KeyEntry,
+        passphrase: SecretString,
+        accept_last_attempt: bool,
+    ) -> CryptoResult<(Vec<PatchLayer>, Vec<String>)> {
+        let mut layers = Vec::new();
+        let mut missing = Vec::new();
+        for (path, patch) in ArchivePatchService::layers_for(manifest, Path::new(encrypted_file)) {
+            if !path.exists() {
+                warn!(
+                    patch_name = %patch.patch_name,
+                    "Recorded patch isn't beside the archive, restoring without it"
+                );
+                missing.push(patch.patch_name);
+                continue;
+            }
+            let encrypted = crypto::read_age_archive(&path).map_err(|e| {
+                CryptoError::InvalidInput(format!(
+                    "Failed to read patch '{}': {}",
+                    patch.patch_name, e
+                ))
+            })?;
+            let payload = Zeroizing::new(self.decrypt_payload(
+                &encrypted,
+                key_id,
+                key_entry,
+                passphrase.clone(),
+                accept_last_attempt,
+            )?);
+            let layer = ArchivePatchService::read_payload(&payload).map_err(|e| {
+                CryptoError::DecryptionFailed(format!("Patch '{}': {}", patch.patch_name, e))
+            })?;
+            if layer.manifest.base_archive_id != patch.manifest.base_archive_id
+                || layer.manifest.vault_id != manifest.vault_id()
+            {
+                return Err(CryptoError::DecryptionFailed(format!(
+                    "Patch '{}' belongs to another archive",
+                    patch.patch_name
+                )));
+            }
+            layers.push(layer);
+        }
+        Ok((layers, missing))
+    }
+
+    /// Process vault manifest embedded in the decrypted archive
+    ///
+    /// Prefers the embedded manifest, compares it with the local external
+    /// manifest, and handles version conflicts. Legacy archives without an
+    /// embedded copy fall back to the external manifest, which is left as is.
+    ///
+    /// # Returns
+    /// (manifest_was_updated, encryption_revision, manifest_resolution)
+    fn process_vault_manifest(
+        &self,
+        embedded: Option<VaultMetadata>,
+        vault_name: &str,
+    ) -> CryptoResult<(bool, Option<u32>, ManifestResolution)> {
+        let resolution = self.embedded_manifest.resolve(embedded, vault_name);
+
+        let Some(bundle_manifest) = resolution.embedded.as_ref() else {
+            let encryption_revision = resolution
+                .external
+                .as_ref()
+                .map(VaultMetadata::encryption_revision);
+            info!(
+                source = ?resolution.source,
+                "No vault manifest found in bundle, skipping version comparison"
+            );
+            return Ok((false, encryption_revision, resolution));
+        };
 
         info!(
             vault = %bundle_manifest.label(),
@@ -324,26 +881,12 @@ fn process_vault_manifest(
         // Get path to local manifest in non-sync storage
         let local_manifest_path = get_vault_manifest_path(&bundle_manifest.vault.sanitized_name)
             .map_err(|e| CryptoError::InvalidInput(format!("Invalid vault name: {}", e)))?;
-
-        // Load local manifest if exists
-        let local_manifest = if local_manifest_path.exists() {
-            match std::fs::read_to_string(&local_manifest_path)
-                .and_then(|c| serde_json::from_str::<VaultMetadata>(&c).map_err(|e| e.into()))
-            {
-                Ok(m) => Some(m),
-                Err(e) => {
-                    warn!(error = ?e, "Failed to load local manifest, will use bundle version");
-                    None
-                }
-            }
-        } else {
-            None
-        };
+        let local_manifest = resolution.external.as_ref();
 
         // Use VersionComparisonService to resolve
         let was_updated = VersionComparisonService::resolve_with_backup(
-            &bundle_manifest,
-            local_manifest.as_ref(),
+            bundle_manifest,
+            local_manifest,
             &local_manifest_path,
         )
         .map_err(|e| {
@@ -352,16 +895,63 @@ fn process_vault_manifest(
 
         // Log conflict message if any
         let comparison =
-            VersionComparisonService::compare_manifests(&bundle_manifest, local_manifest.as_ref());
+            VersionComparisonService::compare_manifests(bundle_manifest, local_manifest);
         if let Some(msg) = VersionComparisonService::get_conflict_message(&comparison) {
             info!(message = %msg, "Version comparison result");
         }
 
-        Ok((
-            was_updated,
-            Some(bundle_manifest.encryption_revision()),
-            Some(bundle_manifest),
-        ))
+        let encryption_revision = Some(bundle_manifest.encryption_revision());
+        Ok((was_updated, encryption_revision, resolution))
+    }
+
+    /// Apply ownership recorded in the manifest to the extracted files
+    ///
+    /// Manifest paths are relative to the selection root, so extracted files
+    /// are matched by content hash, preferring one whose path ends with the
+    /// recorded path.
+    fn restore_file_ownership(
+        &self,
+        extracted_files: &[file_operations::FileInfo],
+        resolution: &ManifestResolution,
+        policy: file_operations::OwnershipPolicy,
+    ) -> file_operations::FidelityReport {
+        let recorded: Vec<(PathBuf, file_operations::FileOwnership)> = resolution
+            .preferred()
+            .map(|manifest| {
+                manifest
+                    .content
+                    .files
+                    .iter()
+                    .filter_map(|entry| {
+                        let ownership = entry.ownership.clone()?;
+                        let file = extracted_files
+                            .iter()
+                            .find(|f| {
+                                f.hash == entry.sha256 && f.path.ends_with(entry.relative_path())
+                            })
+                            .or_else(|| extracted_files.iter().find(|f| f.hash == entry.sha256))?;
+                        Some((file.path.clone(), ownership))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let report = file_operations::apply_ownership(
+            &recorded,
+            policy,
+            &file_operations::SystemUserDatabase,
+        );
+
+        if !report.ownership_fallbacks.is_empty() || !report.warnings.is_empty() {
+            warn!(
+                policy = ?policy,
+                fallbacks = report.ownership_fallbacks.len(),
+                warnings = report.warnings.len(),
+                "Recorded file ownership was not fully restored"
+            );
+        }
+
+        report
     }
 
     /// Restore Key Registry from vault manifest
@@ -551,7 +1141,7 @@ fn clean_internal_files(
                 if is_internal {
                     let file_path = output_dir.join(&file_info.path);
                     if file_path.exists() {
-                        match std::fs::remove_file(&file_path) {
+                        match SecureDeleteService::new().delete_file(&file_path) {
                             Ok(_) => {
                                 debug!(
                                     file = %file_name_str,
@@ -0,0 +1,379 @@
+//! Embedded Manifest Service
+//!
+//! Resolves which vault manifest describes a decrypted archive. The copy
+//! embedded in the archive is authoritative; the external manifest in
+//! non-sync storage is only used for legacy archives that carry no copy.
+//! When both exist and disagree, the differing fields are reported rather
+//! than silently trusting either side.
+
+use crate::prelude::*;
+use crate::services::crypto::domain::{CryptoError, CryptoResult};
+use crate::services::file::infrastructure::file_operations;
+use crate::services::shared::infrastructure::get_vault_manifest_path;
+use crate::services::vault::application::services::{
+    ManifestDiscrepancy, VersionComparisonService,
+};
+use crate::services::vault::domain::models::{AppCompatibility, AppVersion};
+use crate::services::vault::infrastructure::persistence::metadata::VaultMetadata;
+use crate::services::vault::infrastructure::persistence::{ManifestSignatureCheck, ManifestSigner};
+use std::path::PathBuf;
+
+/// Where the manifest describing an archive came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, specta::Type)]
+#[serde(rename_all = "lowercase")]
+pub enum ManifestSource {
+    /// Copy stored inside the encrypted archive
+    Embedded,
+    /// Local external manifest (legacy archives without an embedded copy)
+    External,
+    /// No manifest available
+    None,
+}
+
+/// Embedded and external manifests for an archive, with any disagreement
+#[derive(Debug, Clone)]
+pub struct ManifestResolution {
+    pub source: ManifestSource,
+    pub embedded: Option<VaultMetadata>,
+    pub external: Option<VaultMetadata>,
+    /// Fields that differ when both manifests exist (stale sidecar)
+    pub discrepancies: Vec<ManifestDiscrepancy>,
+    /// Signature check of the external manifest, when there is one
+    pub signature: Option<ManifestSignatureCheck>,
+}
+
+impl ManifestResolution {
+    /// Pair an archive's embedded manifest with the local external manifest
+    pub fn new(embedded: Option<VaultMetadata>, external: Option<VaultMetadata>) -> Self {
+        let source = match (&embedded, &external) {
+            (Some(_), _) => ManifestSource::Embedded,
+            (None, Some(_)) => ManifestSource::External,
+            (None, None) => ManifestSource::None,
+        };
+
+        let discrepancies = match (&embedded, &external) {
+            (Some(embedded), Some(external)) => {
+                VersionComparisonService::find_discrepancies(embedded, external)
+            }
+            _ => Vec::new(),
+        };
+
+        Self {
+            source,
+            embedded,
+            external,
+            discrepancies,
+            signature: None,
+        }
+    }
+
+    /// Manifest to trust: embedded first, external as fallback
+    pub fn preferred(&self) -> Option<&VaultMetadata> {
+        self.embedded.as_ref().or(self.external.as_ref())
+    }
+
+    /// True when both manifests exist and disagree
+    pub fn is_stale(&self) -> bool {
+        !self.discrepancies.is_empty()
+    }
+
+    /// Check the preferred manifest's app version requirements
+    pub fn check_app_version(&self) -> CryptoResult<()> {
+        self.preferred().map_or(Ok(()), check_app_version)
+    }
+}
+
+/// Refuse an archive whose manifest needs a newer app than this one
+///
+/// Metadata that is merely newer than this app is logged and otherwise
+/// ignored, since older apps skip fields they don't know.
+pub fn check_app_version(manifest: &VaultMetadata) -> CryptoResult<()> {
+    let current = AppVersion::current();
+    match manifest.app_requirements().check(current) {
+        AppCompatibility::Supported => Ok(()),
+        AppCompatibility::MissingOptionalFeatures { recommended } => {
+            warn!(
+                vault = %manifest.label(),
+                recommended = %recommended,
+                current = %current,
+                "Archive has metadata from a newer app version; some details will be ignored"
+            );
+            Ok(())
+        }
+        AppCompatibility::TooOld { required } => {
+            error!(
+                vault = %manifest.label(),
+                required = %required,
+                current = %current,
+                "Archive requires a newer app version"
+            );
+            Err(CryptoError::AppTooOld {
+                required: required.to_string(),
+                current: current.to_string(),
+            })
+        }
+    }
+}
+
+/// Service for reading embedded manifests and reconciling them with the sidecar
+#[derive(Debug)]
+pub struct EmbeddedManifestService;
+
+impl EmbeddedManifestService {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Parse the manifest embedded in a decrypted archive payload
+    #[instrument(skip(self, decrypted_data))]
+    pub fn read_embedded(&self, decrypted_data: &[u8]) -> CryptoResult<Option<VaultMetadata>> {
+        let Some(embedded) = file_operations::read_embedded_manifest(decrypted_data)
+            .map_err(|e| CryptoError::InvalidInput(format!("Failed to read archive: {}", e)))?
+        else {
+            debug!("Archive has no embedded manifest");
+            return Ok(None);
+        };
+
+        let manifest: VaultMetadata = serde_json::from_slice(&embedded.contents).map_err(|e| {
+            CryptoError::InvalidInput(format!(
+                "Failed to parse embedded manifest {}: {}",
+                embedded.filename, e
+            ))
+        })?;
+
+        Ok(Some(manifest))
+    }
+
+    /// Load the local external manifest for a vault, if present and readable
+    pub fn load_external(&self, sanitized_name: &str) -> Option<VaultMetadata> {
+        let path = get_vault_manifest_path(sanitized_name).ok()?;
+        if !path.exists() {
+            return None;
+        }
+
+        match std::fs::read_to_string(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|c| serde_json::from_str::<VaultMetadata>(&c).map_err(|e| e.to_string()))
+        {
+            Ok(manifest) => Some(manifest),
+            Err(e) => {
+                warn!(path = %path.display(), error = %e, "Failed to load external manifest");
+                None
+            }
+        }
+    }
+
+    /// Check the signature of a vault's external manifest
+    ///
+    /// `None` when there is no sidecar or it couldn't be read.
+    pub fn check_external_signature(&self, sanitized_name: &str) -> Option<ManifestSignatureCheck> {
+        let path = get_vault_manifest_path(sanitized_name).ok()?;
+        if !path.exists() {
+            return None;
+        }
+
+        let check = ManifestSigner::from_keys_dir()
+            .and_then(|signer| signer.verify_file(&path))
+            .map_err(
+                |e| warn!(path = %path.display(), error = %e, "Manifest signature not checked"),
+            )
+            .ok()?;
+        if !check.is_acceptable() {
+            warn!(
+                vault = %sanitized_name,
+                status = ?check.status,
+                signer = ?check.signer,
+                "External manifest signature does not verify"
+            );
+        }
+        Some(check)
+    }
+
+    /// Resolve manifests for an archive
+    ///
+    /// The external manifest is looked up by the embedded manifest's vault name
+    /// when available, otherwise by `fallback_name` (parsed from the filename).
+    pub fn resolve(
+        &self,
+        embedded: Option<VaultMetadata>,
+        fallback_name: &str,
+    ) -> ManifestResolution {
+        let sanitized_name = embedded
+            .as_ref()
+            .map(|m| m.vault.sanitized_name.clone())
+            .unwrap_or_else(|| fallback_name.to_string());
+        let external = self.load_external(&sanitized_name);
+
+        let mut resolution = ManifestResolution::new(embedded, external);
+        if resolution.external.is_some() {
+            resolution.signature = self.check_external_signature(&sanitized_name);
+        }
+
+        if resolution.is_stale() {
+            warn!(
+                vault = %sanitized_name,
+                fields = ?resolution
+                    .discrepancies
+                    .iter()
+                    .map(|d| d.field.as_str())
+                    .collect::<Vec<_>>(),
+                "External manifest disagrees with embedded manifest"
+            );
+        }
+
+        resolution
+    }
+
+    /// Rewrite the external manifest from an embedded copy
+    ///
+    /// Any existing sidecar is backed up before being replaced.
+    pub fn write_external(&self, manifest: &VaultMetadata) -> CryptoResult<PathBuf> {
+        let path = get_vault_manifest_path(&manifest.vault.sanitized_name)
+            .map_err(|e| CryptoError::InvalidInput(format!("Invalid vault name: {}", e)))?;
+
+        VersionComparisonService::backup_and_replace(manifest, &path).map_err(|e| {
+            CryptoError::IoError(format!("Failed to write external manifest: {}", e))
+        })?;
+
+        info!(
+            vault = %manifest.label(),
+            path = %path.display(),
+            "Rewrote external manifest from embedded copy"
+        );
+
+        Ok(path)
+    }
+}
+
+impl Default for EmbeddedManifestService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::shared::infrastructure::DeviceInfo;
+    use flate2::Compression;
+    use flate2::write::GzEncoder;
+    use tar::{Builder, Header};
+
+    fn test_manifest(revision: u32) -> VaultMetadata {
+        let device_info = DeviceInfo {
+            machine_id: "test-machine".to_string(),
+            machine_label: "test-laptop".to_string(),
+            created_at: chrono::Utc::now(),
+            app_version: "2.0.0".to_string(),
+        };
+
+        let mut manifest = VaultMetadata::new(
+            "vault-001".to_string(),
+            "Family Docs".to_string(),
+            None,
+            "Family-Docs".to_string(),
+            &device_info,
+            None,
+            vec![],
+            vec![],
+            0,
+            0,
+        );
+        manifest.versioning.revision = revision;
+        manifest
+    }
+
+    fn build_payload(entries: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut builder = Builder::new(GzEncoder::new(Vec::new(), Compression::default()));
+        for (path, content) in entries {
+            let mut header = Header::new_gnu();
+            header.set_size(content.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder.append_data(&mut header, path, *content).unwrap();
+        }
+        builder.into_inner().unwrap().finish().unwrap()
+    }
+
+    #[test]
+    fn test_read_embedded_manifest() {
+        let manifest = test_manifest(4);
+        let json = serde_json::to_vec_pretty(&manifest).unwrap();
+        let payload = build_payload(&[("Family-Docs.manifest", &json), ("a.txt", b"a")]);
+
+        let read = EmbeddedManifestService::new()
+            .read_embedded(&payload)
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(read.vault_id(), "vault-001");
+        assert_eq!(read.encryption_revision(), 4);
+    }
+
+    #[test]
+    fn test_read_embedded_rejects_invalid_manifest() {
+        let payload = build_payload(&[("Family-Docs.manifest", b"not json")]);
+
+        assert!(
+            EmbeddedManifestService::new()
+                .read_embedded(&payload)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_embedded_only_archive() {
+        let resolution = ManifestResolution::new(Some(test_manifest(2)), None);
+
+        assert_eq!(resolution.source, ManifestSource::Embedded);
+        assert!(!resolution.is_stale());
+        assert_eq!(resolution.preferred().unwrap().encryption_revision(), 2);
+    }
+
+    #[test]
+    fn test_sidecar_only_legacy_archive() {
+        let resolution = ManifestResolution::new(None, Some(test_manifest(1)));
+
+        assert_eq!(resolution.source, ManifestSource::External);
+        assert!(!resolution.is_stale());
+        assert_eq!(resolution.preferred().unwrap().encryption_revision(), 1);
+    }
+
+    #[test]
+    fn test_mismatched_pair_prefers_embedded_and_reports_fields() {
+        let embedded = test_manifest(3);
+        let mut external = embedded.clone();
+        external.versioning.revision = 1;
+        external.vault.label = "Old Name".to_string();
+
+        let resolution = ManifestResolution::new(Some(embedded), Some(external));
+
+        assert_eq!(resolution.source, ManifestSource::Embedded);
+        assert!(resolution.is_stale());
+        assert_eq!(resolution.preferred().unwrap().label(), "Family Docs");
+
+        let fields: Vec<&str> = resolution
+            .discrepancies
+            .iter()
+            .map(|d| d.field.as_str())
+            .collect();
+        assert_eq!(fields, vec!["label", "revision"]);
+    }
+
+    #[test]
+    fn test_matching_pair_is_not_stale() {
+        let embedded = test_manifest(3);
+        let resolution = ManifestResolution::new(Some(embedded.clone()), Some(embedded));
+
+        assert_eq!(resolution.source, ManifestSource::Embedded);
+        assert!(!resolution.is_stale());
+    }
+
+    #[test]
+    fn test_no_manifest_available() {
+        let resolution = ManifestResolution::new(None, None);
+
+        assert_eq!(resolution.source, ManifestSource::None);
+        assert!(resolution.preferred().is_none());
+    }
+}
@@ -5,6 +5,7 @@
 use crate::prelude::*;
 use crate::services::crypto::application::dtos::EncryptDataInput;
 use crate::services::crypto::domain::{CryptoError, CryptoResult};
+use crate::services::key_management::shared::ContactService;
 use std::path::PathBuf;
 
 #[derive(Debug)]
@@ -61,8 +62,17 @@ pub async fn encrypt_files(&self, input: EncryptDataInput) -> CryptoResult<Strin
         // Step 1: Validate input using dedicated service
         self.file_validation.validate_encrypt_input(&input)?;
 
-        // Step 2: Retrieve and validate encryption key
+        // Step 2: Retrieve and validate encryption key, plus any contacts
         let public_key = self.key_retrieval.get_encryption_key(&input.key_id).await?;
+        let mut public_keys = vec![public_key];
+        for contact in ContactService::new()
+            .resolve(&input.contact_ids)
+            .map_err(|e| CryptoError::InvalidInput(e.to_string()))?
+        {
+            if !public_keys.contains(&contact.recipient) {
+                public_keys.push(contact.recipient);
+            }
+        }
 
         // Step 3: Determine output directory (from original logic)
         let output_dir = if let Some(ref output_path) = input.output_path {
@@ -89,14 +99,14 @@ pub async fn encrypt_files(&self, input: EncryptDataInput) -> CryptoResult<Strin
             .core_encryption
             .encrypt_archive_data(
                 &archive_data,
-                &public_key,
+                &public_keys,
                 &mut progress_manager,
                 &operation_id,
             )
             .await?;
 
-        // Step 6: Write encrypted file and get final path
-        let encrypted_path = self
+        // Step 6: Write encrypted file, replacing any previous archive
+        let written = self
             .core_encryption
             .write_encrypted_file(
                 &encrypted_data,
@@ -105,6 +115,7 @@ pub async fn encrypt_files(&self, input: EncryptDataInput) -> CryptoResult<Strin
                 &operation_id,
             )
             .await?;
+        let encrypted_path = written.path.to_string_lossy().to_string();
 
         // Step 7: Cleanup and final progress
         progress_manager.set_progress(
@@ -119,6 +130,9 @@ pub async fn encrypt_files(&self, input: EncryptDataInput) -> CryptoResult<Strin
         info!(
             encrypted_path = %encrypted_path,
             operation_id = %operation_id,
+            sha256 = %written.sha256,
+            replaced_existing = written.replaced_existing,
+            previous_sha256 = written.previous_sha256.as_deref().unwrap_or_default(),
             "Encryption operation completed successfully"
         );
 
@@ -118,6 +118,7 @@ fn test_validate_empty_key_id_fails() {
             file_paths: vec!["test.txt".to_string()],
             output_name: None,
             output_path: None,
+            contact_ids: vec![],
         };
 
         assert!(service.validate_encrypt_input(&input).is_err());
@@ -131,6 +132,7 @@ fn test_validate_empty_files_fails() {
             file_paths: vec![],
             output_name: None,
             output_path: None,
+            contact_ids: vec![],
         };
 
         assert!(service.validate_encrypt_input(&input).is_err());
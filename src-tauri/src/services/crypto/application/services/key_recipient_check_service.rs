@@ -0,0 +1,257 @@
+//! Key Recipient Check Service
+//!
+//! Tells, before any passphrase is used, whether the selected key is one of
+//! an archive's recipients, and if not which registered keys are.
+//!
+//! YubiKey recipients are matched by their stanza tag. X25519 stanzas are
+//! anonymous, so passphrase keys are matched against the recipients in the
+//! vault's local manifest, and only when the manifest accounts for every
+//! X25519 stanza in the header. Anything less is reported as `Unknown` and
+//! decryption goes ahead as before.
+
+use crate::prelude::*;
+use crate::services::crypto::domain::{CryptoError, CryptoResult};
+use crate::services::crypto::infrastructure::{
+    AgeHeader, is_x25519_recipient, read_age_header_file, yubikey_recipient_tag,
+};
+use crate::services::key_management::shared::{KeyEntry, KeyRegistryService};
+use crate::services::vault::infrastructure::persistence::metadata::VaultMetadata;
+use serde::Serialize;
+use std::collections::HashSet;
+use std::path::Path;
+
+/// Whether the selected key can open an archive
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, specta::Type)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum KeyRecipientCheck {
+    /// The key is one of the archive's recipients
+    Recipient,
+    /// The key isn't a recipient; these registered keys are (labels only)
+    NotARecipient { matching_key_labels: Vec<String> },
+    /// The header doesn't reveal enough to tell
+    Unknown,
+}
+
+/// Service matching registered keys against an archive's header
+#[derive(Debug)]
+pub struct KeyRecipientCheckService {
+    key_registry_service: KeyRegistryService,
+}
+
+impl KeyRecipientCheckService {
+    pub fn new() -> Self {
+        Self {
+            key_registry_service: KeyRegistryService::new(),
+        }
+    }
+
+    /// Check `key_id` against the header of `encrypted_file`
+    ///
+    /// `manifest` is the vault's local manifest, used to identify the
+    /// anonymous X25519 recipients.
+    #[instrument(skip(self, manifest))]
+    pub fn check(
+        &self,
+        encrypted_file: &Path,
+        key_id: &str,
+        manifest: Option<&VaultMetadata>,
+    ) -> CryptoResult<KeyRecipientCheck> {
+        let header = read_age_header_file(encrypted_file)?;
+        let registry = self
+            .key_registry_service
+            .load_registry()
+            .map_err(|e| CryptoError::ConfigurationError(e.to_string()))?;
+        let selected = registry
+            .get_key(key_id)
+            .ok_or_else(|| CryptoError::InvalidInput(format!("Key '{}' not found", key_id)))?;
+
+        let manifest_recipients: Option<Vec<&str>> = manifest.map(|manifest| {
+            manifest
+                .encryption
+                .recipients
+                .iter()
+                .map(|r| r.public_key.as_str())
+                .collect()
+        });
+        let owned_keys = registry.owned_keys().into_iter().map(|(_, entry)| entry);
+
+        let check = Self::evaluate(
+            &header,
+            selected,
+            owned_keys,
+            manifest_recipients.as_deref(),
+        );
+        debug!(key_id = %key_id, check = ?check, "Checked key against archive recipients");
+        Ok(check)
+    }
+
+    /// Match `selected` and `owned_keys` against `header`
+    pub fn evaluate<'a>(
+        header: &AgeHeader,
+        selected: &KeyEntry,
+        owned_keys: impl IntoIterator<Item = &'a KeyEntry>,
+        manifest_recipients: Option<&[&str]>,
+    ) -> KeyRecipientCheck {
+        // Archives encrypted straight to a passphrase have no key recipients
+        if header.is_passphrase_encrypted() {
+            return KeyRecipientCheck::Unknown;
+        }
+
+        let tags: HashSet<&str> = header.piv_p256_tags().into_iter().collect();
+        let x25519: Option<HashSet<&str>> = match manifest_recipients {
+            _ if header.x25519_count() == 0 => Some(HashSet::new()),
+            Some(recipients) => {
+                let known: HashSet<&str> = recipients
+                    .iter()
+                    .copied()
+                    .filter(|r| is_x25519_recipient(r))
+                    .collect();
+                (known.len() == header.x25519_count()).then_some(known)
+            }
+            None => None,
+        };
+
+        let is_recipient = |entry: &KeyEntry| match entry {
+            KeyEntry::Yubikey { recipient, .. } => {
+                yubikey_recipient_tag(recipient).map(|tag| tags.contains(tag.as_str()))
+            }
+            KeyEntry::Passphrase { public_key, .. } | KeyEntry::Recipient { public_key, .. } => {
+                x25519
+                    .as_ref()
+                    .map(|known| known.contains(public_key.as_str()))
+            }
+            // Nothing was ever encrypted to a reserved slot
+            KeyEntry::YubikeyPending { .. } => Some(false),
+        };
+
+        match is_recipient(selected) {
+            Some(true) => KeyRecipientCheck::Recipient,
+            None => KeyRecipientCheck::Unknown,
+            Some(false) => {
+                let mut matching_key_labels: Vec<String> = owned_keys
+                    .into_iter()
+                    .filter(|entry| is_recipient(entry) == Some(true))
+                    .map(|entry| entry.label().to_string())
+                    .collect();
+                matching_key_labels.sort();
+                matching_key_labels.dedup();
+                KeyRecipientCheck::NotARecipient {
+                    matching_key_labels,
+                }
+            }
+        }
+    }
+}
+
+impl Default for KeyRecipientCheckService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::crypto::infrastructure::read_age_header;
+    use crate::services::key_management::shared::domain::models::key_lifecycle::KeyLifecycleStatus;
+    use age::x25519::Identity;
+    use chrono::Utc;
+    use std::io::Write;
+
+    fn passphrase_key(label: &str, public_key: &str) -> KeyEntry {
+        KeyEntry::Passphrase {
+            label: label.to_string(),
+            created_at: Utc::now(),
+            last_used: None,
+            public_key: public_key.to_string(),
+            key_filename: format!("{label}.agekey.enc"),
+            lifecycle_status: KeyLifecycleStatus::Active,
+            status_history: vec![],
+            vault_associations: vec![],
+            deactivated_at: None,
+            previous_lifecycle_status: None,
+            key_location: None,
+            passphrase_policy: 0,
+        }
+    }
+
+    fn header_for(recipients: &[&str]) -> AgeHeader {
+        let parsed: Vec<age::x25519::Recipient> =
+            recipients.iter().map(|r| r.parse().unwrap()).collect();
+        let encryptor =
+            age::Encryptor::with_recipients(parsed.iter().map(|r| r as &dyn age::Recipient))
+                .unwrap();
+        let mut encrypted = Vec::new();
+        let mut writer = encryptor.wrap_output(&mut encrypted).unwrap();
+        writer.write_all(b"archive").unwrap();
+        writer.finish().unwrap();
+        read_age_header(&encrypted[..]).unwrap()
+    }
+
+    fn public_key() -> String {
+        Identity::generate().to_public().to_string()
+    }
+
+    #[test]
+    fn test_selected_key_b_suggests_key_a() {
+        let (a, b) = (public_key(), public_key());
+        let key_a = passphrase_key("Key A", &a);
+        let key_b = passphrase_key("Key B", &b);
+        let header = header_for(&[&a]);
+
+        let check = KeyRecipientCheckService::evaluate(
+            &header,
+            &key_b,
+            [&key_a, &key_b],
+            Some(&[a.as_str()][..]),
+        );
+        assert_eq!(
+            check,
+            KeyRecipientCheck::NotARecipient {
+                matching_key_labels: vec!["Key A".to_string()]
+            }
+        );
+
+        let check = KeyRecipientCheckService::evaluate(
+            &header,
+            &key_a,
+            [&key_a, &key_b],
+            Some(&[a.as_str()][..]),
+        );
+        assert_eq!(check, KeyRecipientCheck::Recipient);
+    }
+
+    #[test]
+    fn test_unknown_external_recipient_suggests_nothing() {
+        let external = public_key();
+        let key_a = passphrase_key("Key A", &public_key());
+        let header = header_for(&[&external]);
+
+        let check = KeyRecipientCheckService::evaluate(
+            &header,
+            &key_a,
+            [&key_a],
+            Some(&[external.as_str()][..]),
+        );
+        assert_eq!(
+            check,
+            KeyRecipientCheck::NotARecipient {
+                matching_key_labels: vec![]
+            }
+        );
+    }
+
+    #[test]
+    fn test_unaccounted_x25519_recipients_are_unknown() {
+        let (a, b) = (public_key(), public_key());
+        let key_b = passphrase_key("Key B", &b);
+        let header = header_for(&[&a, &public_key()]);
+
+        // No local manifest, or one listing fewer recipients than the header
+        let none = KeyRecipientCheckService::evaluate(&header, &key_b, [&key_b], None);
+        let partial =
+            KeyRecipientCheckService::evaluate(&header, &key_b, [&key_b], Some(&[a.as_str()][..]));
+        assert_eq!(none, KeyRecipientCheck::Unknown);
+        assert_eq!(partial, KeyRecipientCheck::Unknown);
+    }
+}
@@ -5,6 +5,7 @@
 
 use crate::prelude::*;
 use crate::services::crypto::domain::{CryptoError, CryptoResult};
+use crate::services::key_management::shared::domain::models::contact::is_contact_id;
 use crate::services::key_management::shared::{KeyEntry, KeyRegistryService};
 
 /// Service for retrieving decryption key information
@@ -25,6 +26,14 @@ pub fn new() -> Self {
     pub fn get_decryption_key_info(&self, key_id: &str) -> CryptoResult<KeyEntry> {
         debug!(key_id = %key_id, "Retrieving decryption key from registry");
 
+        // Only the contact holds the private key for a contact's recipient
+        if is_contact_id(key_id) {
+            return Err(CryptoError::InvalidInput(format!(
+                "'{}' is a contact; contacts can only be encrypted to, not decrypted with",
+                key_id
+            )));
+        }
+
         self.key_registry_service.get_key(key_id).map_err(|e| {
             error!(key_id = %key_id, error = %e, "Failed to retrieve key from registry");
             CryptoError::InvalidInput(format!("Key '{}' not found: {}", key_id, e))
@@ -47,4 +56,13 @@ fn test_key_retrieval_decryption_service_creation() {
         let _service = KeyRetrievalDecryptionService::new();
         // Just verify creation works
     }
+
+    #[test]
+    fn test_contacts_never_decrypt() {
+        let service = KeyRetrievalDecryptionService::new();
+        let result = service.get_decryption_key_info("contact-sam-1700000000000");
+        assert!(
+            matches!(result, Err(CryptoError::InvalidInput(message)) if message.contains("contact"))
+        );
+    }
 }
@@ -4,6 +4,7 @@
 
 use crate::prelude::*;
 use crate::services::file::infrastructure::file_operations;
+use crate::services::vault::infrastructure::persistence::metadata::VaultMetadata;
 use std::path::Path;
 
 /// Service for manifest verification and restoration
@@ -43,26 +44,18 @@ pub fn verify_manifest(
                 "Found manifest file, attempting verification"
             );
 
-            // Try to load and verify the manifest
-            match file_operations::archive_manifest::Manifest::load(&manifest_path) {
-                Ok(manifest) => {
-                    match file_operations::verify_manifest(
-                        &manifest,
-                        extracted_files,
-                        &file_operations::FileOpsConfig::default(),
-                    ) {
-                        Ok(()) => {
-                            info!("Manifest verification successful");
-                            true
-                        }
-                        Err(e) => {
-                            warn!(error = %e, "Manifest verification failed");
-                            false
-                        }
-                    }
+            // Stream the manifest so large ones are never loaded whole
+            match file_operations::verify_manifest_file(
+                &manifest_path,
+                extracted_files,
+                &file_operations::FileOpsConfig::default(),
+            ) {
+                Ok(()) => {
+                    info!("Manifest verification successful");
+                    true
                 }
                 Err(e) => {
-                    warn!(error = %e, "Failed to load manifest");
+                    warn!(error = %e, "Manifest verification failed");
                     false
                 }
             }
@@ -72,6 +65,28 @@ pub fn verify_manifest(
             true
         }
     }
+
+    /// Verify that the directories a vault manifest records were restored
+    ///
+    /// Manifests written before directory entries existed are not checked.
+    #[instrument(skip(self, manifest))]
+    pub fn verify_directories(&self, manifest: &VaultMetadata, output_path: &Path) -> bool {
+        let Some(directories) = manifest.archived_directory_paths() else {
+            debug!(
+                schema = %manifest.schema,
+                "Manifest predates directory entries, skipping directory checks"
+            );
+            return true;
+        };
+
+        match file_operations::verify_directories(output_path, &directories) {
+            Ok(()) => true,
+            Err(e) => {
+                warn!(error = %e, "Directory verification failed");
+                false
+            }
+        }
+    }
 }
 
 impl Default for ManifestVerificationService {
@@ -1,27 +1,74 @@
+pub mod archive_browse_service;
 pub mod archive_extraction_service;
+pub mod archive_migration_service;
 pub mod archive_orchestration_service;
+pub mod archive_path_remap_service;
+pub mod benchmark_service;
+pub mod cleanup_session_service;
 pub mod core_encryption_service;
+pub mod decrypt_options_service;
 pub mod decryption_orchestration_service;
+pub mod embedded_manifest_service;
 pub mod encryption_service;
 pub mod file_validation_service;
+pub mod key_recipient_check_service;
 pub mod key_retrieval_decryption_service;
 pub mod key_retrieval_service;
 pub mod manifest_verification_service;
+pub mod panic_lock_service;
 pub mod passphrase_decryption_service;
+pub mod recovery_share_decryption_service;
+pub mod recovery_simulation_service;
+pub mod salvage_decryption_service;
+pub mod tool_independence_service;
 // vault_encryption_service removed - use VaultBundleEncryptionService in vault domain instead
+pub mod yubikey_batch_decryption_service;
 pub mod yubikey_decryption_service;
 
+pub use archive_browse_service::{
+    ArchiveBrowseService, BROWSE_IDLE_TIMEOUT, BrowseSessionInfo, BrowseSessionRegistry,
+};
 pub use archive_extraction_service::ArchiveExtractionService;
+pub use archive_migration_service::{
+    ArchiveMigrationService, migration_reasons, vault_parameters, verify_staged_files,
+};
 pub use archive_orchestration_service::ArchiveOrchestrationService;
+pub use archive_path_remap_service::ArchivePathRemapService;
+pub use benchmark_service::BenchmarkService;
+pub use cleanup_session_service::{CleanupSessionService, MAX_SESSION_TTL_MINUTES};
 pub use core_encryption_service::CoreEncryptionService;
+pub use decrypt_options_service::{
+    DecryptOption, DecryptOptionKind, DecryptOptions, DecryptOptionsService, UnavailableReason,
+};
 pub use decryption_orchestration_service::{
-    DecryptionInput, DecryptionOrchestrationService, DecryptionOutput,
+    DecryptionInput, DecryptionOrchestrationService, DecryptionOutput, RegeneratedManifest,
+};
+pub use embedded_manifest_service::{
+    EmbeddedManifestService, ManifestResolution, ManifestSource, check_app_version,
 };
 pub use encryption_service::EncryptionService;
 pub use file_validation_service::FileValidationService;
+pub use key_recipient_check_service::{KeyRecipientCheck, KeyRecipientCheckService};
 pub use key_retrieval_decryption_service::KeyRetrievalDecryptionService;
 pub use key_retrieval_service::KeyRetrievalService;
 pub use manifest_verification_service::ManifestVerificationService;
+pub use panic_lock_service::PanicLockService;
 pub use passphrase_decryption_service::PassphraseDecryptionService;
+pub use recovery_share_decryption_service::RecoveryShareDecryptionService;
+pub use recovery_simulation_service::{
+    LiveProfile, RecoveryCredentials, RecoverySimulationReport, RecoverySimulationService,
+    SimulationSource, SimulationStep, SimulationStepKind, SimulationStepStatus,
+};
+pub use salvage_decryption_service::{
+    LostFile, PartialFile, RecoveredFile, SalvageDecryptionService, SalvageReport,
+};
+pub use tool_independence_service::{
+    ArchiveEncoding, PayloadCompression, ProprietaryFraming, TRANSCRIPT_HEADING,
+    ToolIndependenceReport, ToolIndependenceService, render_transcript,
+};
 // VaultEncryptionService removed - use VaultBundleEncryptionService in vault domain instead
+pub use yubikey_batch_decryption_service::{
+    BatchArchiveResult, BatchArchiveStatus, BatchArchiveTarget, BatchDecryptionOptions,
+    BatchDecryptionReport, YubiKeyBatchDecryptionService,
+};
 pub use yubikey_decryption_service::YubiKeyDecryptionService;
@@ -0,0 +1,480 @@
+//! Panic Lock Service
+//!
+//! Clears the sensitive state the app holds outside the vaults, for someone
+//! about to hand over their device. Running operations are asked to stop
+//! first so nothing new is decrypted behind the lock; then browse snapshots
+//! are zeroized, PTY sessions killed (removing their temporary identity
+//! files), recovery shares held for copying zeroized, the app's copy
+//! cleared from the clipboard, and every pending cleanup of decrypted output
+//! runs immediately. One entry goes to the operation log.
+//!
+//! The app stores no passphrases or PINs in the OS credential store and
+//! keeps no unlocked state for hidden vaults, so neither has anything to
+//! clear. Vault manifests, archives and key files are left alone.
+//!
+//! Every step runs even if an earlier one fails, and running it again only
+//! finds nothing left to clear.
+
+use super::{BrowseSessionRegistry, CleanupSessionService};
+use crate::prelude::*;
+use crate::services::crypto::domain::models::{
+    PANIC_LOCK_CONFIRMATION, PanicLockFailure, PanicLockPreview, PanicLockReport, PanicLockStep,
+};
+use crate::services::crypto::domain::{CryptoError, CryptoResult};
+use crate::services::key_management::passphrase::HeldRecoveryShares;
+use crate::services::key_management::yubikey::infrastructure::pty::PtySessionRegistry;
+use crate::services::shared::infrastructure::progress::{
+    OperationKind, active_operations, cancel_operations,
+};
+use crate::services::vault::application::services::{
+    ClipboardBackend, ClipboardGuard, OperationLogService,
+};
+use crate::services::vault::infrastructure::persistence::{
+    APP_LOG_SCOPE, LoggedOperation, OperationLog, OperationLogEntry,
+};
+use chrono::Utc;
+use std::path::PathBuf;
+use std::time::Instant;
+
+/// Service wiping cached sensitive state
+pub struct PanicLockService<'a> {
+    browse: &'a BrowseSessionRegistry,
+    pty: &'a PtySessionRegistry,
+    recovery_shares: &'a HeldRecoveryShares,
+    clipboard: &'a ClipboardGuard,
+    cleanup: CleanupSessionService,
+    running_operations: fn() -> Vec<OperationKind>,
+    cancel_operations: fn() -> Vec<OperationKind>,
+    /// Operation log location; the config directory's log when `None`
+    audit_log: Option<PathBuf>,
+}
+
+impl PanicLockService<'static> {
+    pub fn new() -> Self {
+        Self {
+            browse: BrowseSessionRegistry::global(),
+            pty: PtySessionRegistry::global(),
+            recovery_shares: HeldRecoveryShares::global(),
+            clipboard: ClipboardGuard::global(),
+            cleanup: CleanupSessionService::new(),
+            running_operations: active_operations,
+            cancel_operations,
+            audit_log: None,
+        }
+    }
+}
+
+impl Default for PanicLockService<'static> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a> PanicLockService<'a> {
+    /// Service over the given registries and stores
+    #[allow(clippy::too_many_arguments)]
+    pub fn with(
+        browse: &'a BrowseSessionRegistry,
+        pty: &'a PtySessionRegistry,
+        recovery_shares: &'a HeldRecoveryShares,
+        clipboard: &'a ClipboardGuard,
+        cleanup: CleanupSessionService,
+        running_operations: fn() -> Vec<OperationKind>,
+        cancel_operations: fn() -> Vec<OperationKind>,
+        audit_log: PathBuf,
+    ) -> Self {
+        Self {
+            browse,
+            pty,
+            recovery_shares,
+            clipboard,
+            cleanup,
+            running_operations,
+            cancel_operations,
+            audit_log: Some(audit_log),
+        }
+    }
+
+    /// What a panic lock would clear, for the first confirmation step
+    pub fn preview(&self) -> CryptoResult<PanicLockPreview> {
+        Ok(PanicLockPreview {
+            running_operations: names((self.running_operations)()),
+            browse_sessions: self.browse.list().len(),
+            pty_sessions: self.pty.list().iter().filter(|s| !s.terminated).count(),
+            recovery_share_sets: self.recovery_shares.held_vaults(Instant::now()),
+            clipboard_copy: self.clipboard.is_guarding(),
+            cleanup_sessions: self.cleanup.list()?.len(),
+        })
+    }
+
+    /// Clear all cached sensitive state once the user typed the confirmation
+    ///
+    /// `clipboard` is the system clipboard, `None` when it isn't available;
+    /// a copy waiting to be cleared is then reported as a failure.
+    pub fn lock(
+        &self,
+        confirmation: Option<&str>,
+        clipboard: Option<&dyn ClipboardBackend>,
+    ) -> CryptoResult<PanicLockReport> {
+        if confirmation != Some(PANIC_LOCK_CONFIRMATION) {
+            return Err(CryptoError::InvalidInput(format!(
+                "Type '{}' to confirm the panic lock",
+                PANIC_LOCK_CONFIRMATION
+            )));
+        }
+
+        warn!("Panic lock requested");
+        let mut report = PanicLockReport {
+            locked_at: Utc::now(),
+            cancelled_operations: names((self.cancel_operations)()),
+            browse_sessions_closed: self.browse.stop_all(),
+            pty_sessions_killed: 0,
+            recovery_share_sets_forgotten: 0,
+            clipboard_cleared: false,
+            cleanups: Vec::new(),
+            failures: Vec::new(),
+        };
+
+        for (session_id, killed) in self.pty.kill_all() {
+            match killed {
+                Ok(()) => report.pty_sessions_killed += 1,
+                Err(e) => report.failures.push(PanicLockFailure {
+                    step: PanicLockStep::KillPtySessions,
+                    message: format!("{session_id}: {e}"),
+                }),
+            }
+        }
+
+        report.recovery_share_sets_forgotten = self.recovery_shares.forget_all(Instant::now());
+
+        match clipboard {
+            Some(backend) => match self.clipboard.clear_now(backend) {
+                Ok(cleared) => report.clipboard_cleared = cleared,
+                Err(e) => report.failures.push(PanicLockFailure {
+                    step: PanicLockStep::ClearClipboard,
+                    message: e.to_string(),
+                }),
+            },
+            None if self.clipboard.is_guarding() => report.failures.push(PanicLockFailure {
+                step: PanicLockStep::ClearClipboard,
+                message: "The clipboard isn't available".to_string(),
+            }),
+            None => {}
+        }
+
+        match self.cleanup.run_all() {
+            Ok(cleanups) => report.cleanups = cleanups,
+            Err(e) => report.failures.push(PanicLockFailure {
+                step: PanicLockStep::RunCleanups,
+                message: e.to_string(),
+            }),
+        }
+
+        if let Err(message) = self.record(&report) {
+            report.failures.push(PanicLockFailure {
+                step: PanicLockStep::RecordAudit,
+                message,
+            });
+        }
+
+        info!(
+            cancelled_operations = report.cancelled_operations.len(),
+            browse_sessions = report.browse_sessions_closed,
+            pty_sessions = report.pty_sessions_killed,
+            recovery_share_sets = report.recovery_share_sets_forgotten,
+            clipboard_cleared = report.clipboard_cleared,
+            cleanups = report.cleanups.len(),
+            failures = report.failures.len(),
+            "Panic lock finished"
+        );
+        Ok(report)
+    }
+
+    fn record(&self, report: &PanicLockReport) -> Result<(), String> {
+        let entry = OperationLogEntry::new(
+            LoggedOperation::PanicLock,
+            APP_LOG_SCOPE,
+            format!(
+                "Panic lock: {} operations cancelled, {} browse sessions closed, \
+                 {} PTY sessions killed, {} recovery share sets forgotten, \
+                 clipboard {}, {} cleanups run, {} failures",
+                report.cancelled_operations.len(),
+                report.browse_sessions_closed,
+                report.pty_sessions_killed,
+                report.recovery_share_sets_forgotten,
+                if report.clipboard_cleared {
+                    "cleared"
+                } else {
+                    "untouched"
+                },
+                report.cleanups.len(),
+                report.failures.len()
+            ),
+        );
+        match &self.audit_log {
+            Some(path) => OperationLog::record_in(path, entry)
+                .map(|_| ())
+                .map_err(|e| e.to_string()),
+            None => OperationLogService::new()
+                .record(entry)
+                .map_err(|e| e.to_string()),
+        }
+    }
+}
+
+fn names(operations: Vec<OperationKind>) -> Vec<String> {
+    operations.iter().map(ToString::to_string).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::crypto::application::services::BROWSE_IDLE_TIMEOUT;
+    use crate::services::crypto::domain::models::CleanupOutcome;
+    use crate::services::crypto::infrastructure::archive_browse::test_archive;
+    use crate::services::crypto::infrastructure::{SnapshotLimits, SnapshotStore};
+    use crate::services::file::infrastructure::file_operations::FileInfo;
+    use crate::services::key_management::passphrase::RecoveryShareError;
+    use crate::services::key_management::yubikey::infrastructure::pty::WatchdogConfig;
+    use crate::services::shared::infrastructure::clock::testing::{FakeClock, service};
+    use crate::services::vault::application::services::ClipboardCopy;
+    use crate::services::vault::domain::models::ClipboardKind;
+    use crate::types::DurationMs;
+    use portable_pty::ChildKiller;
+    use sha2::{Digest, Sha256};
+    use std::cell::RefCell;
+    use std::fs;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use tempfile::TempDir;
+
+    #[derive(Debug, Clone, Default)]
+    struct FakeChild {
+        killed: Arc<AtomicBool>,
+    }
+
+    impl ChildKiller for FakeChild {
+        fn kill(&mut self) -> std::io::Result<()> {
+            self.killed.store(true, Ordering::SeqCst);
+            Ok(())
+        }
+
+        fn clone_killer(&self) -> Box<dyn ChildKiller + Send + Sync> {
+            Box::new(self.clone())
+        }
+    }
+
+    #[derive(Default)]
+    struct FakeClipboard {
+        text: RefCell<Option<String>>,
+    }
+
+    impl ClipboardBackend for FakeClipboard {
+        fn write_text(&self, text: &str) -> Result<(), String> {
+            *self.text.borrow_mut() = Some(text.to_string());
+            Ok(())
+        }
+
+        fn read_text(&self) -> Result<Option<String>, String> {
+            Ok(self.text.borrow().clone())
+        }
+
+        fn clear(&self) -> Result<(), String> {
+            *self.text.borrow_mut() = None;
+            Ok(())
+        }
+    }
+
+    const SHARE: &str = "barqly-share-1-2-Zx8PqR2mT5vW9yB4";
+
+    fn snapshot() -> SnapshotStore {
+        let archive = test_archive(&[("docs/passport.pdf", b"0123456789")]);
+        SnapshotStore::from_archive(&archive, |_| true, SnapshotLimits::default()).unwrap()
+    }
+
+    // Stand-ins for the process-wide operation registry, which other tests share
+    fn one_running() -> Vec<OperationKind> {
+        vec![OperationKind::Decryption]
+    }
+
+    fn none_running() -> Vec<OperationKind> {
+        Vec::new()
+    }
+
+    #[test]
+    fn test_panic_lock_clears_every_cache_and_leaves_vaults_alone() {
+        let temp = TempDir::new().unwrap();
+        let clock = FakeClock::at("2026-03-01T00:00:00Z");
+
+        // Vault data and keys that must survive
+        let vault_file = temp.path().join("Family.age");
+        let manifest = temp.path().join("Family.manifest");
+        let key_file = temp.path().join("keys").join("family.agekey.enc");
+        fs::create_dir_all(key_file.parent().unwrap()).unwrap();
+        fs::write(&vault_file, b"age-encryption.org/v1").unwrap();
+        fs::write(&manifest, b"{}").unwrap();
+        fs::write(&key_file, b"encrypted key").unwrap();
+
+        // Decrypted output awaiting cleanup
+        let output_dir = temp.path().join("output");
+        fs::create_dir_all(&output_dir).unwrap();
+        let extracted = output_dir.join("passport.pdf");
+        fs::write(&extracted, b"secret").unwrap();
+        let cleanup = CleanupSessionService::at(temp.path().join("sessions.json"), service(&clock));
+        cleanup
+            .register(
+                &output_dir,
+                &[FileInfo {
+                    path: extracted.clone(),
+                    size: 6,
+                    modified: Utc::now(),
+                    hash: hex::encode(Sha256::digest(b"secret")),
+                    #[cfg(unix)]
+                    permissions: 0o644,
+                }],
+                60,
+            )
+            .unwrap();
+
+        // An open browse snapshot and a YubiKey session with an identity file
+        let browse = BrowseSessionRegistry::new(BROWSE_IDLE_TIMEOUT);
+        browse.open("vault-1", "archive-1", snapshot()).unwrap();
+        let pty = PtySessionRegistry::new(WatchdogConfig::default());
+        let identity = temp.path().join("identity.txt");
+        fs::write(&identity, "AGE-PLUGIN-YUBIKEY-TEST").unwrap();
+        let child = FakeChild::default();
+        let session = pty.register("age", Box::new(child.clone()), vec![identity.clone()]);
+
+        // New recovery shares, one of them copied to the clipboard
+        let recovery_shares = HeldRecoveryShares::new();
+        recovery_shares.hold(
+            "vault-1",
+            &[
+                SHARE.to_string(),
+                "barqly-share-2-2-Qw3ErT5yU7iO9pA1".to_string(),
+            ],
+            Instant::now(),
+        );
+        let clipboard_guard =
+            ClipboardGuard::with_audit_log(temp.path().join("clipboard_log.json"));
+        let clipboard = FakeClipboard::default();
+        clipboard_guard
+            .copy(
+                &clipboard,
+                ClipboardCopy {
+                    kind: ClipboardKind::RecoveryCode,
+                    value_ref: "1",
+                    log_scope: "vault-1",
+                    value: SHARE,
+                },
+                DurationMs::from_secs(30),
+                Utc::now(),
+            )
+            .unwrap();
+
+        let audit_log = temp.path().join("operation_log.json");
+        let panic = PanicLockService::with(
+            &browse,
+            &pty,
+            &recovery_shares,
+            &clipboard_guard,
+            CleanupSessionService::at(temp.path().join("sessions.json"), service(&clock)),
+            one_running,
+            one_running,
+            audit_log.clone(),
+        );
+
+        let preview = panic.preview().unwrap();
+        assert_eq!(preview.browse_sessions, 1);
+        assert_eq!(preview.pty_sessions, 1);
+        assert_eq!(preview.recovery_share_sets, 1);
+        assert!(preview.clipboard_copy);
+        assert_eq!(preview.cleanup_sessions, 1);
+
+        // The typed confirmation is required
+        for confirmation in [None, Some("lock"), Some("UNLOCK")] {
+            assert!(matches!(
+                panic.lock(confirmation, Some(&clipboard)),
+                Err(CryptoError::InvalidInput(_))
+            ));
+        }
+        assert_eq!(browse.list().len(), 1);
+        assert_eq!(clipboard.read_text().unwrap().as_deref(), Some(SHARE));
+
+        let report = panic
+            .lock(Some(PANIC_LOCK_CONFIRMATION), Some(&clipboard))
+            .unwrap();
+        assert!(report.is_complete(), "{report:?}");
+        assert_eq!(report.cancelled_operations, vec!["decryption"]);
+        assert_eq!(report.browse_sessions_closed, 1);
+        assert_eq!(report.pty_sessions_killed, 1);
+        assert_eq!(report.recovery_share_sets_forgotten, 1);
+        assert!(report.clipboard_cleared);
+        assert!(matches!(
+            report.cleanups[0].outcome,
+            CleanupOutcome::Deleted {
+                files_removed: 1,
+                ..
+            }
+        ));
+
+        assert!(browse.list().is_empty());
+        assert!(child.killed.load(Ordering::SeqCst));
+        assert!(session.check().is_err());
+        assert!(!identity.exists());
+        assert!(!extracted.exists());
+        assert!(cleanup.list().unwrap().is_empty());
+        assert!(matches!(
+            recovery_shares.share("vault-1", 1, Instant::now()),
+            Err(RecoveryShareError::ShareNotHeld(_))
+        ));
+        assert_eq!(clipboard.read_text().unwrap(), None);
+        assert!(!clipboard_guard.is_guarding());
+
+        assert_eq!(fs::read(&vault_file).unwrap(), b"age-encryption.org/v1");
+        assert_eq!(fs::read(&manifest).unwrap(), b"{}");
+        assert_eq!(fs::read(&key_file).unwrap(), b"encrypted key");
+
+        let log = OperationLog::load_from(&audit_log).unwrap();
+        assert_eq!(log.entries.len(), 1);
+        assert_eq!(log.entries[0].operation, LoggedOperation::PanicLock);
+        assert_eq!(log.entries[0].vault_id, APP_LOG_SCOPE);
+    }
+
+    #[test]
+    fn test_panic_lock_is_idempotent() {
+        let temp = TempDir::new().unwrap();
+        let clock = FakeClock::at("2026-03-01T00:00:00Z");
+        let browse = BrowseSessionRegistry::new(BROWSE_IDLE_TIMEOUT);
+        let pty = PtySessionRegistry::new(WatchdogConfig::default());
+        browse.open("vault-1", "archive-1", snapshot()).unwrap();
+        let _session = pty.register("age", Box::new(FakeChild::default()), vec![]);
+        let recovery_shares = HeldRecoveryShares::new();
+        recovery_shares.hold("vault-1", &[SHARE.to_string()], Instant::now());
+        let clipboard_guard = ClipboardGuard::new();
+
+        let panic = PanicLockService::with(
+            &browse,
+            &pty,
+            &recovery_shares,
+            &clipboard_guard,
+            CleanupSessionService::at(temp.path().join("sessions.json"), service(&clock)),
+            none_running,
+            none_running,
+            temp.path().join("operation_log.json"),
+        );
+
+        let first = panic.lock(Some(PANIC_LOCK_CONFIRMATION), None).unwrap();
+        assert!(first.cleared_anything());
+        assert_eq!(first.recovery_share_sets_forgotten, 1);
+        let second = panic.lock(Some(PANIC_LOCK_CONFIRMATION), None).unwrap();
+        assert!(!second.cleared_anything());
+        assert!(second.is_complete());
+
+        let preview = panic.preview().unwrap();
+        assert_eq!(
+            preview.browse_sessions + preview.pty_sessions + preview.recovery_share_sets,
+            0
+        );
+    }
+}
@@ -2,12 +2,15 @@
 //!
 //! Handles decryption using passphrase-protected private keys.
 
+use crate::error::StorageError;
 use crate::prelude::*;
 use crate::services::crypto::domain::{CryptoError, CryptoResult};
 use crate::services::crypto::infrastructure as crypto;
 use crate::services::key_management::passphrase;
 use crate::services::key_management::shared;
-use age::secrecy::SecretString;
+use crate::services::key_management::shared::KeyEntry;
+use crate::services::key_management::shared::domain::models::KeyFileLocation;
+use age::secrecy::{ExposeSecret, SecretString};
 
 /// Service for passphrase-based decryption operations
 #[derive(Debug)]
@@ -18,36 +21,126 @@ pub fn new() -> Self {
         Self
     }
 
+    /// Decrypt data with a registered passphrase key and what the user typed
+    #[instrument(skip(self, encrypted_data, key_entry, passphrase))]
+    pub fn decrypt_with_registered_key(
+        &self,
+        encrypted_data: &[u8],
+        key_id: &str,
+        key_entry: &KeyEntry,
+        passphrase: SecretString,
+    ) -> CryptoResult<Vec<u8>> {
+        let private_key = self.unlock_registered_key(key_id, key_entry, passphrase)?;
+        self.decrypt_payload(encrypted_data, &private_key)
+    }
+
     /// Decrypt data using passphrase-protected key
-    #[instrument(skip(self, encrypted_data, passphrase))]
     pub fn decrypt_with_passphrase(
         &self,
         encrypted_data: &[u8],
         key_filename: &str,
         passphrase: SecretString,
+    ) -> CryptoResult<Vec<u8>> {
+        self.decrypt_with_key_file(encrypted_data, key_filename, None, passphrase)
+    }
+
+    /// Decrypt data using a passphrase-protected key that may have been
+    /// relinked outside the keys directory
+    #[instrument(skip(self, encrypted_data, key_location, passphrase))]
+    pub fn decrypt_with_key_file(
+        &self,
+        encrypted_data: &[u8],
+        key_filename: &str,
+        key_location: Option<&KeyFileLocation>,
+        passphrase: SecretString,
     ) -> CryptoResult<Vec<u8>> {
         debug!(
             key_filename = %key_filename,
+            relinked = key_location.is_some(),
             encrypted_data_size = encrypted_data.len(),
             "Starting passphrase-based decryption"
         );
 
-        // Load the encrypted private key
-        let encrypted_key = shared::load_encrypted_key(key_filename).map_err(|e| {
+        let private_key = self.unlock_private_key(key_filename, key_location, passphrase)?;
+        self.decrypt_payload(encrypted_data, &private_key)
+    }
+
+    fn decrypt_payload(
+        &self,
+        encrypted_data: &[u8],
+        private_key: &crypto::PrivateKey,
+    ) -> CryptoResult<Vec<u8>> {
+        // Decrypt the vault data using the private key
+        let decrypted_data = crypto::decrypt_data(encrypted_data, private_key).map_err(|e| {
             error!(
-                key_filename = %key_filename,
                 error = %e,
-                "Failed to load encrypted private key"
+                "Failed to decrypt vault data"
             );
-            CryptoError::ConfigurationError(format!("Failed to load encrypted key: {}", e))
+            CryptoError::DecryptionFailed(format!("Failed to decrypt data: {}", e))
         })?;
 
-        debug!(
-            key_filename = %key_filename,
-            encrypted_key_size = encrypted_key.len(),
-            "Successfully loaded encrypted private key"
+        info!(
+            decrypted_data_size = decrypted_data.len(),
+            "Successfully decrypted vault data with passphrase"
         );
 
+        Ok(decrypted_data)
+    }
+
+    /// Unlock a registered passphrase key with what the user typed
+    ///
+    /// The passphrase is normalized as the key's policy requires; keys from
+    /// before normalization are re-protected once they unlock.
+    pub fn unlock_registered_key(
+        &self,
+        key_id: &str,
+        key_entry: &KeyEntry,
+        passphrase: SecretString,
+    ) -> CryptoResult<crypto::PrivateKey> {
+        let KeyEntry::Passphrase {
+            key_filename,
+            key_location,
+            passphrase_policy,
+            ..
+        } = key_entry
+        else {
+            return Err(CryptoError::InvalidInput(format!(
+                "Key '{}' is not a passphrase key",
+                key_id
+            )));
+        };
+        let encrypted_key = self.load_key_file(key_filename, key_location.as_ref())?;
+
+        passphrase::UnlockService::new()
+            .unlock(
+                key_id,
+                &encrypted_key,
+                passphrase.expose_secret(),
+                *passphrase_policy,
+            )
+            .map_err(|e| {
+                error!(
+                    key_id = %key_id,
+                    error = %e,
+                    "Failed to decrypt private key with passphrase"
+                );
+                CryptoError::DecryptionFailed(format!("Failed to decrypt private key: {}", e))
+            })
+    }
+
+    /// Load a passphrase-protected key file and decrypt its private key
+    ///
+    /// The passphrase is used exactly as given, as suits generated secrets
+    /// such as recovery passphrases; typed passphrases go through
+    /// [`Self::unlock_registered_key`].
+    pub fn unlock_private_key(
+        &self,
+        key_filename: &str,
+        key_location: Option<&KeyFileLocation>,
+        passphrase: SecretString,
+    ) -> CryptoResult<crypto::PrivateKey> {
+        let encrypted_key = self.load_key_file(key_filename, key_location)?;
+
         // Decrypt the private key with passphrase
         let private_key =
             passphrase::decrypt_private_key(&encrypted_key, passphrase).map_err(|e| {
@@ -64,21 +157,41 @@ pub fn decrypt_with_passphrase(
             "Successfully decrypted private key"
         );
 
-        // Decrypt the vault data using the private key
-        let decrypted_data = crypto::decrypt_data(encrypted_data, &private_key).map_err(|e| {
+        Ok(private_key)
+    }
+
+    fn load_key_file(
+        &self,
+        key_filename: &str,
+        key_location: Option<&KeyFileLocation>,
+    ) -> CryptoResult<Vec<u8>> {
+        let encrypted_key = shared::infrastructure::load_passphrase_key_file(
+            key_filename,
+            key_location,
+        )
+        .map_err(|e| {
             error!(
+                key_filename = %key_filename,
                 error = %e,
-                "Failed to decrypt vault data"
+                "Failed to load encrypted private key"
             );
-            CryptoError::DecryptionFailed(format!("Failed to decrypt data: {}", e))
+            match e {
+                StorageError::KeyMediaNotPresent { volume_label } => {
+                    CryptoError::KeyMediaNotPresent { volume_label }
+                }
+                e => {
+                    CryptoError::ConfigurationError(format!("Failed to load encrypted key: {}", e))
+                }
+            }
         })?;
 
-        info!(
-            decrypted_data_size = decrypted_data.len(),
-            "Successfully decrypted vault data with passphrase"
+        debug!(
+            key_filename = %key_filename,
+            encrypted_key_size = encrypted_key.len(),
+            "Successfully loaded encrypted private key"
         );
 
-        Ok(decrypted_data)
+        Ok(encrypted_key)
     }
 }
 
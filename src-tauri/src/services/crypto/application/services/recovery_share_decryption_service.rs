@@ -0,0 +1,93 @@
+//! Recovery Share Decryption Service
+//!
+//! Decrypts a vault using Shamir recovery shares: the shares rebuild the
+//! passphrase of the vault's recovery identity, which then decrypts as any
+//! passphrase key would.
+
+use super::{
+    ArchiveExtractionService, EmbeddedManifestService, PassphraseDecryptionService,
+    check_app_version,
+};
+use crate::prelude::*;
+use crate::services::crypto::domain::{CryptoError, CryptoResult};
+use crate::services::crypto::infrastructure as crypto;
+use crate::services::file::infrastructure::file_operations::{ExtractionResult, PathLimitStrategy};
+use crate::services::key_management::passphrase::{PassphraseManager, RecoveryShareError};
+use std::path::Path;
+
+/// Service for decrypting vaults with recovery shares
+pub struct RecoveryShareDecryptionService {
+    passphrase_manager: PassphraseManager,
+    passphrase_decryption: PassphraseDecryptionService,
+    archive_extraction: ArchiveExtractionService,
+    embedded_manifest: EmbeddedManifestService,
+}
+
+impl RecoveryShareDecryptionService {
+    pub fn new() -> Self {
+        Self {
+            passphrase_manager: PassphraseManager::new(),
+            passphrase_decryption: PassphraseDecryptionService::new(),
+            archive_extraction: ArchiveExtractionService::new(),
+            embedded_manifest: EmbeddedManifestService::new(),
+        }
+    }
+
+    /// Reconstruct the recovery secret from shares and decrypt the archive
+    #[instrument(skip(self, shares))]
+    pub fn decrypt(
+        &self,
+        vault_id: &str,
+        shares: &[String],
+        encrypted_file: &Path,
+        output_dir: &Path,
+    ) -> CryptoResult<ExtractionResult> {
+        let recovery = self
+            .passphrase_manager
+            .reconstruct_recovery_secret(vault_id, shares)
+            .map_err(|e| match e {
+                RecoveryShareError::Share(_) | RecoveryShareError::NotConfigured(_) => {
+                    CryptoError::InvalidInput(e.to_string())
+                }
+                RecoveryShareError::VerificationFailed => {
+                    CryptoError::DecryptionFailed(e.to_string())
+                }
+                _ => CryptoError::ConfigurationError(e.to_string()),
+            })?;
+
+        let encrypted_data = crypto::read_age_archive(encrypted_file).map_err(|e| {
+            CryptoError::FileNotFound(format!("{}: {}", encrypted_file.display(), e))
+        })?;
+
+        let decrypted_data = self.passphrase_decryption.decrypt_with_passphrase(
+            &encrypted_data,
+            &recovery.key_filename,
+            recovery.passphrase,
+        )?;
+        if let Some(manifest) = self.embedded_manifest.read_embedded(&decrypted_data)? {
+            check_app_version(&manifest)?;
+        }
+
+        std::fs::create_dir_all(output_dir).map_err(|e| CryptoError::IoError(e.to_string()))?;
+
+        let result = self.archive_extraction.extract_archive(
+            &decrypted_data,
+            output_dir,
+            PathLimitStrategy::default(),
+        )?;
+
+        info!(
+            vault_id,
+            file_count = result.files.len(),
+            "Vault decrypted with recovery shares"
+        );
+
+        Ok(result)
+    }
+}
+
+impl Default for RecoveryShareDecryptionService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,1186 @@
+//! Recovery Simulation Service
+//!
+//! Rehearses a restore the way an executor would do it on a new machine,
+//! with nothing but the recovery kit and the archive. The rehearsal runs in a
+//! sandbox: a fresh temporary data directory with its own `PathProvider`
+//! root, into which only the kit's files are imported. The key registry, key
+//! store and manifests of the live profile are never opened; when the kit
+//! lacks something, the live profile is only checked for whether it exists,
+//! to tell "only on this machine" from "nowhere". Either way it is a gap.
+//!
+//! A recovery kit is a folder holding `<vault>-RECOVERY.txt`, the external
+//! manifest `<vault>.manifest` and the vault's `.agekey.enc` key files.
+//!
+//! Every write goes through the sandbox, which refuses paths outside its
+//! root, and the sandbox is deleted when the simulation ends. A YubiKey
+//! decryption still stages through the system temporary folder, as it
+//! always does.
+
+use crate::error::StorageError;
+use crate::prelude::*;
+use crate::services::crypto::application::services::EmbeddedManifestService;
+use crate::services::crypto::domain::{CryptoError, CryptoResult};
+use crate::services::crypto::infrastructure as crypto;
+use crate::services::key_management::passphrase::domain::LEGACY_PASSPHRASE_POLICY;
+use crate::services::key_management::passphrase::unwrap_private_key;
+use crate::services::key_management::yubikey::infrastructure::pty::age_ops::decrypt_data_with_yubikey_pty;
+use crate::services::shared::infrastructure::path_management::{
+    PathProvider, get_keys_dir, get_vault_manifest_dirs, get_vaults_directory,
+};
+use crate::services::vault::infrastructure::persistence::metadata::{
+    RecipientType, VaultFileEntry, VaultMetadata,
+};
+use age::secrecy::{ExposeSecret, SecretString};
+use flate2::read::GzDecoder;
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tempfile::TempDir;
+use zeroize::Zeroizing;
+
+/// Manifest entries the test decryption checks against the archive
+pub const SIMULATION_SAMPLE_SIZE: usize = 5;
+
+const INSTRUCTIONS_SUFFIX: &str = "-RECOVERY.txt";
+const MANIFEST_SUFFIX: &str = ".manifest";
+const KEY_FILE_SUFFIX: &str = ".agekey.enc";
+
+/// What the executor brings besides the kit
+pub enum RecoveryCredentials {
+    /// Passphrase of a key file in the kit
+    Passphrase(SecretString),
+    /// PIN of an attached YubiKey the vault is encrypted to
+    YubiKey { pin: SecretString },
+}
+
+/// A step of the rehearsed restore
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, specta::Type)]
+#[serde(rename_all = "snake_case")]
+pub enum SimulationStepKind {
+    /// Importing `<vault>-RECOVERY.txt`
+    Instructions,
+    /// Importing `<vault>.manifest`
+    ExternalManifest,
+    /// Importing a passphrase key file
+    KeyFile,
+    /// Reading who the archive is encrypted to
+    Analysis,
+    /// Unlocking a key with the supplied credentials
+    KeyUnlock,
+    /// Decrypting the archive and checking a sample of its files
+    TestDecryption,
+}
+
+/// Where what a step needed came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, specta::Type)]
+#[serde(rename_all = "snake_case")]
+pub enum SimulationSource {
+    Kit,
+    Archive,
+    /// Only this machine's profile has it
+    LiveProfile,
+    Missing,
+}
+
+/// How a step went
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, specta::Type)]
+#[serde(rename_all = "snake_case")]
+pub enum SimulationStepStatus {
+    Passed,
+    /// The restore needs something the kit doesn't hold
+    Gap,
+    Failed,
+    /// Not attempted, e.g. without credentials
+    Skipped,
+}
+
+/// One step of the report
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct SimulationStep {
+    pub kind: SimulationStepKind,
+    pub status: SimulationStepStatus,
+    /// `None` when the step needed nothing
+    pub source: Option<SimulationSource>,
+    pub detail: String,
+}
+
+impl SimulationStep {
+    fn new(
+        kind: SimulationStepKind,
+        status: SimulationStepStatus,
+        source: Option<SimulationSource>,
+        detail: impl Into<String>,
+    ) -> Self {
+        Self {
+            kind,
+            status,
+            source,
+            detail: detail.into(),
+        }
+    }
+}
+
+/// Step-by-step outcome of a rehearsed restore
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct RecoverySimulationReport {
+    /// Vault the kit's files were matched to the archive by
+    pub vault_name: String,
+    pub steps: Vec<SimulationStep>,
+    /// Manifest entries the test decryption checked
+    pub sampled_files: usize,
+    /// The sandbox is gone
+    pub sandbox_removed: bool,
+}
+
+impl RecoverySimulationReport {
+    /// No step failed and the kit held everything the steps needed
+    pub fn is_complete(&self) -> bool {
+        self.steps.iter().all(|step| {
+            matches!(
+                step.status,
+                SimulationStepStatus::Passed | SimulationStepStatus::Skipped
+            )
+        })
+    }
+
+    /// Steps that needed something the kit doesn't hold
+    pub fn gaps(&self) -> impl Iterator<Item = &SimulationStep> {
+        self.steps
+            .iter()
+            .filter(|step| step.status == SimulationStepStatus::Gap)
+    }
+
+    /// The archive was decrypted and its sampled files matched
+    pub fn decryption_verified(&self) -> bool {
+        self.steps.iter().any(|step| {
+            step.kind == SimulationStepKind::TestDecryption
+                && step.status == SimulationStepStatus::Passed
+        })
+    }
+}
+
+/// This machine's profile, only ever checked for what a kit lacks
+#[derive(Debug, Clone, Default)]
+pub struct LiveProfile {
+    pub keys_dir: Option<PathBuf>,
+    pub manifest_dirs: Vec<PathBuf>,
+    pub vaults_dir: Option<PathBuf>,
+}
+
+impl LiveProfile {
+    /// Locations of the profile in use
+    pub fn current() -> Self {
+        Self {
+            keys_dir: get_keys_dir().ok(),
+            manifest_dirs: get_vault_manifest_dirs().unwrap_or_default(),
+            vaults_dir: get_vaults_directory().ok(),
+        }
+    }
+
+    fn source(found: bool) -> SimulationSource {
+        if found {
+            SimulationSource::LiveProfile
+        } else {
+            SimulationSource::Missing
+        }
+    }
+
+    fn instructions(&self, vault_name: &str) -> SimulationSource {
+        Self::source(self.vaults_dir.as_ref().is_some_and(|dir| {
+            dir.join(format!("{vault_name}{INSTRUCTIONS_SUFFIX}"))
+                .is_file()
+        }))
+    }
+
+    fn manifest(&self, vault_name: &str) -> SimulationSource {
+        let file_name = format!("{vault_name}{MANIFEST_SUFFIX}");
+        Self::source(
+            self.manifest_dirs
+                .iter()
+                .any(|dir| dir.join(&file_name).is_file()),
+        )
+    }
+
+    fn key_file(&self, key_filename: &str) -> SimulationSource {
+        Self::source(
+            self.keys_dir
+                .as_ref()
+                .is_some_and(|dir| dir.join(key_filename).is_file()),
+        )
+    }
+}
+
+/// Observes every path the sandbox is asked to write
+pub type SandboxWriteHook = Arc<dyn Fn(&Path) + Send + Sync>;
+
+/// Fresh profile the kit is imported into
+struct RecoverySandbox {
+    dir: TempDir,
+    paths: PathProvider,
+    on_write: Option<SandboxWriteHook>,
+}
+
+impl RecoverySandbox {
+    fn create(on_write: Option<SandboxWriteHook>) -> CryptoResult<Self> {
+        let dir = tempfile::Builder::new()
+            .prefix("barqly-recovery-simulation-")
+            .tempdir()
+            .map_err(|e| CryptoError::IoError(format!("Failed to create recovery sandbox: {e}")))?;
+        let paths = PathProvider::sandboxed(dir.path().to_path_buf());
+        Ok(Self {
+            dir,
+            paths,
+            on_write,
+        })
+    }
+
+    fn root(&self) -> &Path {
+        self.dir.path()
+    }
+
+    fn location(&self, dir: Result<PathBuf, StorageError>) -> CryptoResult<PathBuf> {
+        dir.map_err(|e| CryptoError::ConfigurationError(e.to_string()))
+    }
+
+    /// Copy `source` into `dir` of the sandbox
+    fn import(&self, source: &Path, dir: &Path) -> CryptoResult<PathBuf> {
+        let file_name = source.file_name().ok_or_else(|| {
+            CryptoError::InvalidInput(format!("Not a file: {}", source.display()))
+        })?;
+        let contents = fs::read(source).map_err(|e| {
+            CryptoError::IoError(format!("Failed to read {}: {e}", source.display()))
+        })?;
+        let target = dir.join(file_name);
+        self.write(&target, &contents)?;
+        Ok(target)
+    }
+
+    /// Write `contents` to `path`, refusing anything outside the sandbox
+    fn write(&self, path: &Path, contents: &[u8]) -> CryptoResult<()> {
+        if let Some(hook) = &self.on_write {
+            hook(path);
+        }
+        if !path.starts_with(self.root()) {
+            return Err(CryptoError::PermissionDenied(format!(
+                "{} is outside the recovery sandbox",
+                path.display()
+            )));
+        }
+
+        let write = || -> io::Result<()> {
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::write(path, contents)
+        };
+        write()
+            .map_err(|e| CryptoError::IoError(format!("Failed to write {}: {e}", path.display())))
+    }
+
+    /// Delete the sandbox; true once it's gone
+    fn tear_down(self) -> bool {
+        let root = self.root().to_path_buf();
+        if let Err(e) = self.dir.close() {
+            warn!(sandbox = %root.display(), error = %e, "Failed to delete recovery sandbox");
+        }
+        !root.exists()
+    }
+}
+
+/// Files found in a recovery kit folder, by name
+#[derive(Debug, Default)]
+struct KitContents {
+    /// Vault name → `<vault>-RECOVERY.txt`
+    instructions: BTreeMap<String, PathBuf>,
+    /// Vault name → `<vault>.manifest`
+    manifests: BTreeMap<String, PathBuf>,
+    /// File name → `.agekey.enc` file
+    key_files: BTreeMap<String, PathBuf>,
+}
+
+impl KitContents {
+    fn scan(dir: &Path) -> CryptoResult<Self> {
+        let entries = fs::read_dir(dir).map_err(|e| {
+            CryptoError::IoError(format!(
+                "Failed to read recovery kit {}: {e}",
+                dir.display()
+            ))
+        })?;
+
+        let mut kit = Self::default();
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if !path.is_file() {
+                continue;
+            }
+            let Some(name) = path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .map(str::to_string)
+            else {
+                continue;
+            };
+            if let Some(vault) = name.strip_suffix(INSTRUCTIONS_SUFFIX) {
+                kit.instructions.insert(vault.to_string(), path);
+            } else if let Some(vault) = name.strip_suffix(MANIFEST_SUFFIX) {
+                kit.manifests.insert(vault.to_string(), path);
+            } else if name.ends_with(KEY_FILE_SUFFIX) {
+                kit.key_files.insert(name, path);
+            }
+        }
+        Ok(kit)
+    }
+
+    /// The vault name the kit knows that `archive_path` is named after
+    ///
+    /// Archives may carry a date suffix (`Vault-2025-01-13.age`), so the
+    /// longest kit name the archive name starts with wins. Without one, the
+    /// archive name itself is used.
+    fn vault_name_for(&self, archive_path: &Path) -> String {
+        let file_name = archive_path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let stem = file_name.strip_suffix(".age").unwrap_or(&file_name);
+
+        self.instructions
+            .keys()
+            .chain(self.manifests.keys())
+            .filter(|name| {
+                stem == name.as_str()
+                    || stem
+                        .strip_prefix(name.as_str())
+                        .is_some_and(|rest| rest.starts_with('-'))
+            })
+            .max_by_key(|name| name.len())
+            .cloned()
+            .unwrap_or_else(|| stem.to_string())
+    }
+}
+
+/// Service rehearsing restores from a recovery kit
+pub struct RecoverySimulationService {
+    live: LiveProfile,
+    on_write: Option<SandboxWriteHook>,
+}
+
+impl std::fmt::Debug for RecoverySimulationService {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RecoverySimulationService")
+            .field("live", &self.live)
+            .field("write_hook", &self.on_write.is_some())
+            .finish()
+    }
+}
+
+impl RecoverySimulationService {
+    pub fn new() -> Self {
+        Self::with_live_profile(LiveProfile::current())
+    }
+
+    pub fn with_live_profile(live: LiveProfile) -> Self {
+        Self {
+            live,
+            on_write: None,
+        }
+    }
+
+    /// Call `hook` with every path the sandbox is asked to write
+    #[cfg(test)]
+    fn with_write_hook(mut self, hook: SandboxWriteHook) -> Self {
+        self.on_write = Some(hook);
+        self
+    }
+
+    /// Rehearse restoring `archive_path` from the kit at `kit_path`
+    ///
+    /// `kit_path` is the kit folder or any file in it. Without credentials
+    /// the kit is imported and the archive analyzed, but nothing decrypted.
+    /// Gaps and failed checks are reported, not returned as errors.
+    #[instrument(skip(self, credentials))]
+    pub fn simulate(
+        &self,
+        kit_path: &Path,
+        archive_path: &Path,
+        credentials: Option<&RecoveryCredentials>,
+    ) -> CryptoResult<RecoverySimulationReport> {
+        let kit_dir = match kit_path.parent() {
+            _ if kit_path.is_dir() => kit_path,
+            Some(parent) if kit_path.is_file() => parent,
+            _ => {
+                return Err(CryptoError::FileNotFound(kit_path.display().to_string()));
+            }
+        };
+        if !archive_path.is_file() {
+            return Err(CryptoError::FileNotFound(
+                archive_path.display().to_string(),
+            ));
+        }
+
+        let kit = KitContents::scan(kit_dir)?;
+        let vault_name = kit.vault_name_for(archive_path);
+
+        let sandbox = RecoverySandbox::create(self.on_write.clone())?;
+        let mut rehearsal = Rehearsal {
+            sandbox: &sandbox,
+            kit: &kit,
+            live: &self.live,
+            vault_name: &vault_name,
+            steps: Vec::new(),
+            sampled_files: 0,
+        };
+        let outcome = rehearsal.run(archive_path, credentials);
+        let (steps, sampled_files) = (rehearsal.steps, rehearsal.sampled_files);
+        let sandbox_removed = sandbox.tear_down();
+        outcome?;
+
+        let report = RecoverySimulationReport {
+            vault_name,
+            steps,
+            sampled_files,
+            sandbox_removed,
+        };
+        info!(
+            vault = %report.vault_name,
+            complete = report.is_complete(),
+            gaps = report.gaps().count(),
+            decryption_verified = report.decryption_verified(),
+            sandbox_removed,
+            "Simulated recovery from kit"
+        );
+        Ok(report)
+    }
+}
+
+impl Default for RecoverySimulationService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// One run of the simulation, collecting steps as it goes
+struct Rehearsal<'a> {
+    sandbox: &'a RecoverySandbox,
+    kit: &'a KitContents,
+    live: &'a LiveProfile,
+    vault_name: &'a str,
+    steps: Vec<SimulationStep>,
+    sampled_files: usize,
+}
+
+impl Rehearsal<'_> {
+    fn push(
+        &mut self,
+        kind: SimulationStepKind,
+        status: SimulationStepStatus,
+        source: Option<SimulationSource>,
+        detail: impl Into<String>,
+    ) {
+        self.steps
+            .push(SimulationStep::new(kind, status, source, detail));
+    }
+
+    fn run(
+        &mut self,
+        archive_path: &Path,
+        credentials: Option<&RecoveryCredentials>,
+    ) -> CryptoResult<()> {
+        self.import_instructions()?;
+        let manifest = self.import_manifest()?;
+        let key_files = self.import_key_files(manifest.as_ref())?;
+        let header = self.analyze(archive_path);
+
+        let Some(credentials) = credentials else {
+            for kind in [
+                SimulationStepKind::KeyUnlock,
+                SimulationStepKind::TestDecryption,
+            ] {
+                self.push(
+                    kind,
+                    SimulationStepStatus::Skipped,
+                    None,
+                    "No passphrase or YubiKey PIN was supplied",
+                );
+            }
+            return Ok(());
+        };
+
+        let payload = match credentials {
+            RecoveryCredentials::Passphrase(passphrase) => {
+                self.decrypt_with_passphrase(archive_path, &key_files, passphrase)
+            }
+            RecoveryCredentials::YubiKey { pin } => {
+                self.decrypt_with_yubikey(archive_path, manifest.as_ref(), header.as_ref(), pin)
+            }
+        };
+        match payload {
+            Some(payload) => self.check_sample(&payload, manifest.as_ref()),
+            None => self.push(
+                SimulationStepKind::TestDecryption,
+                SimulationStepStatus::Skipped,
+                None,
+                "No key was unlocked",
+            ),
+        }
+        Ok(())
+    }
+
+    fn import_instructions(&mut self) -> CryptoResult<()> {
+        let file_name = format!("{}{INSTRUCTIONS_SUFFIX}", self.vault_name);
+        match self.kit.instructions.get(self.vault_name) {
+            Some(path) => {
+                let dir = self
+                    .sandbox
+                    .location(self.sandbox.paths.user_vaults_dir())?;
+                self.sandbox.import(path, &dir)?;
+                self.push(
+                    SimulationStepKind::Instructions,
+                    SimulationStepStatus::Passed,
+                    Some(SimulationSource::Kit),
+                    format!("Imported {file_name}"),
+                );
+            }
+            None => self.push(
+                SimulationStepKind::Instructions,
+                SimulationStepStatus::Gap,
+                Some(self.live.instructions(self.vault_name)),
+                format!("The kit has no {file_name}"),
+            ),
+        }
+        Ok(())
+    }
+
+    fn import_manifest(&mut self) -> CryptoResult<Option<VaultMetadata>> {
+        let file_name = format!("{}{MANIFEST_SUFFIX}", self.vault_name);
+        let Some(path) = self.kit.manifests.get(self.vault_name) else {
+            self.push(
+                SimulationStepKind::ExternalManifest,
+                SimulationStepStatus::Gap,
+                Some(self.live.manifest(self.vault_name)),
+                format!("The kit has no {file_name}"),
+            );
+            return Ok(None);
+        };
+
+        let dir = self
+            .sandbox
+            .location(self.sandbox.paths.vaults_manifest_dir())?;
+        let imported = self.sandbox.import(path, &dir)?;
+        let parsed = fs::read_to_string(&imported)
+            .map_err(|e| e.to_string())
+            .and_then(|c| serde_json::from_str::<VaultMetadata>(&c).map_err(|e| e.to_string()));
+        match parsed {
+            Ok(manifest) => {
+                self.push(
+                    SimulationStepKind::ExternalManifest,
+                    SimulationStepStatus::Passed,
+                    Some(SimulationSource::Kit),
+                    format!(
+                        "Imported {file_name}: {} files, {} keys",
+                        manifest.content.files.len(),
+                        manifest.recipients().len()
+                    ),
+                );
+                Ok(Some(manifest))
+            }
+            Err(e) => {
+                self.push(
+                    SimulationStepKind::ExternalManifest,
+                    SimulationStepStatus::Failed,
+                    Some(SimulationSource::Kit),
+                    format!("{file_name} can't be read: {e}"),
+                );
+                Ok(None)
+            }
+        }
+    }
+
+    /// Import the key files the manifest lists, or every key file in the
+    /// kit without a manifest to go by
+    fn import_key_files(&mut self, manifest: Option<&VaultMetadata>) -> CryptoResult<Vec<PathBuf>> {
+        let wanted: Vec<String> = match manifest {
+            Some(manifest) => manifest
+                .recipients()
+                .iter()
+                .filter_map(|r| match &r.recipient_type {
+                    RecipientType::Passphrase { key_filename } => Some(key_filename.clone()),
+                    _ => None,
+                })
+                .collect(),
+            None => self.kit.key_files.keys().cloned().collect(),
+        };
+
+        let dir = self.sandbox.location(self.sandbox.paths.keys_dir())?;
+        let mut imported = Vec::new();
+        for key_filename in wanted {
+            match self.kit.key_files.get(&key_filename) {
+                Some(path) => {
+                    imported.push(self.sandbox.import(path, &dir)?);
+                    self.push(
+                        SimulationStepKind::KeyFile,
+                        SimulationStepStatus::Passed,
+                        Some(SimulationSource::Kit),
+                        format!("Imported {key_filename}"),
+                    );
+                }
+                None => self.push(
+                    SimulationStepKind::KeyFile,
+                    SimulationStepStatus::Gap,
+                    Some(self.live.key_file(&key_filename)),
+                    format!("The kit has no {key_filename}"),
+                ),
+            }
+        }
+        Ok(imported)
+    }
+
+    fn analyze(&mut self, archive_path: &Path) -> Option<crypto::AgeHeader> {
+        match crypto::read_age_header_file(archive_path) {
+            Ok(header) => {
+                self.push(
+                    SimulationStepKind::Analysis,
+                    SimulationStepStatus::Passed,
+                    Some(SimulationSource::Archive),
+                    format!(
+                        "Encrypted to {} keys and {} YubiKeys",
+                        header.x25519_count(),
+                        header.piv_p256_tags().len()
+                    ),
+                );
+                Some(header)
+            }
+            Err(e) => {
+                self.push(
+                    SimulationStepKind::Analysis,
+                    SimulationStepStatus::Failed,
+                    Some(SimulationSource::Archive),
+                    format!("The archive can't be read: {e}"),
+                );
+                None
+            }
+        }
+    }
+
+    fn decrypt_with_passphrase(
+        &mut self,
+        archive_path: &Path,
+        key_files: &[PathBuf],
+        passphrase: &SecretString,
+    ) -> Option<Zeroizing<Vec<u8>>> {
+        if key_files.is_empty() {
+            self.push(
+                SimulationStepKind::KeyUnlock,
+                SimulationStepStatus::Gap,
+                Some(SimulationSource::Missing),
+                "The kit holds no key file for the passphrase to unlock",
+            );
+            return None;
+        }
+
+        // Key files are read back from the sandbox, never from the kit
+        let unlocked = key_files.iter().find_map(|path| {
+            let encrypted_key = fs::read(path).ok()?;
+            unwrap_private_key(
+                &encrypted_key,
+                passphrase.expose_secret(),
+                LEGACY_PASSPHRASE_POLICY,
+            )
+            .ok()
+            .map(|unlocked| (path, unlocked.private_key))
+        });
+        let Some((path, private_key)) = unlocked else {
+            self.push(
+                SimulationStepKind::KeyUnlock,
+                SimulationStepStatus::Failed,
+                Some(SimulationSource::Kit),
+                "The passphrase doesn't unlock any key file in the kit",
+            );
+            return None;
+        };
+        self.push(
+            SimulationStepKind::KeyUnlock,
+            SimulationStepStatus::Passed,
+            Some(SimulationSource::Kit),
+            format!(
+                "Unlocked {}",
+                path.file_name().unwrap_or_default().to_string_lossy()
+            ),
+        );
+
+        let decrypted = crypto::read_age_archive(archive_path)
+            .map_err(|e| e.to_string())
+            .and_then(|data| crypto::decrypt_data(&data, &private_key).map_err(|e| e.to_string()));
+        self.decrypted(decrypted)
+    }
+
+    fn decrypt_with_yubikey(
+        &mut self,
+        archive_path: &Path,
+        manifest: Option<&VaultMetadata>,
+        header: Option<&crypto::AgeHeader>,
+        pin: &SecretString,
+    ) -> Option<Zeroizing<Vec<u8>>> {
+        let Some(manifest) = manifest else {
+            self.push(
+                SimulationStepKind::KeyUnlock,
+                SimulationStepStatus::Gap,
+                Some(self.live.manifest(self.vault_name)),
+                "Without the manifest the YubiKey's identity is unknown",
+            );
+            return None;
+        };
+
+        // Prefer a YubiKey the archive header names
+        let tags = header.map(|h| h.piv_p256_tags()).unwrap_or_default();
+        let mut yubikeys: Vec<_> = manifest
+            .recipients()
+            .iter()
+            .filter(|r| matches!(r.recipient_type, RecipientType::YubiKey { .. }))
+            .collect();
+        yubikeys.sort_by_key(|r| {
+            !crypto::yubikey_recipient_tag(&r.public_key)
+                .is_some_and(|tag| tags.contains(&tag.as_str()))
+        });
+        let Some(recipient) = yubikeys.first() else {
+            self.push(
+                SimulationStepKind::KeyUnlock,
+                SimulationStepStatus::Failed,
+                Some(SimulationSource::Kit),
+                "The manifest lists no YubiKey",
+            );
+            return None;
+        };
+        let RecipientType::YubiKey {
+            serial,
+            slot,
+            identity_tag,
+            ..
+        } = &recipient.recipient_type
+        else {
+            return None;
+        };
+
+        let decrypted = crypto::read_age_archive(archive_path)
+            .map_err(|e| e.to_string())
+            .and_then(|data| {
+                decrypt_data_with_yubikey_pty(
+                    &data,
+                    serial,
+                    *slot,
+                    &recipient.public_key,
+                    identity_tag,
+                    pin.expose_secret(),
+                )
+                .map_err(|e| e.to_string())
+            });
+        self.push(
+            SimulationStepKind::KeyUnlock,
+            if decrypted.is_ok() {
+                SimulationStepStatus::Passed
+            } else {
+                SimulationStepStatus::Failed
+            },
+            Some(SimulationSource::Kit),
+            format!("YubiKey '{}' from the kit's manifest", recipient.label),
+        );
+        self.decrypted(decrypted)
+    }
+
+    fn decrypted(&mut self, decrypted: Result<Vec<u8>, String>) -> Option<Zeroizing<Vec<u8>>> {
+        match decrypted {
+            Ok(payload) => Some(Zeroizing::new(payload)),
+            Err(e) => {
+                self.push(
+                    SimulationStepKind::TestDecryption,
+                    SimulationStepStatus::Failed,
+                    Some(SimulationSource::Archive),
+                    format!("The archive didn't decrypt: {e}"),
+                );
+                None
+            }
+        }
+    }
+
+    /// Check a sample of the manifest's files against the decrypted payload
+    ///
+    /// The manifest embedded in the archive is preferred over the kit's.
+    fn check_sample(&mut self, payload: &[u8], kit_manifest: Option<&VaultMetadata>) {
+        let embedded = EmbeddedManifestService::new()
+            .read_embedded(payload)
+            .ok()
+            .flatten();
+        let Some(manifest) = embedded.as_ref().or(kit_manifest) else {
+            self.push(
+                SimulationStepKind::TestDecryption,
+                SimulationStepStatus::Failed,
+                Some(SimulationSource::Archive),
+                "The archive decrypted but holds no manifest to check its files against",
+            );
+            return;
+        };
+
+        let sample = sample_entries(manifest);
+        let (status, detail) = match unmatched_entries(payload, &sample) {
+            Ok(unmatched) if unmatched.is_empty() => (
+                SimulationStepStatus::Passed,
+                format!("{} sampled files match the manifest", sample.len()),
+            ),
+            Ok(unmatched) => (
+                SimulationStepStatus::Failed,
+                format!(
+                    "{} of {} sampled files are missing or differ: {}",
+                    unmatched.len(),
+                    sample.len(),
+                    unmatched.join(", ")
+                ),
+            ),
+            Err(e) => (
+                SimulationStepStatus::Failed,
+                format!("The decrypted archive can't be read: {e}"),
+            ),
+        };
+        self.sampled_files = sample.len();
+        self.push(
+            SimulationStepKind::TestDecryption,
+            status,
+            Some(SimulationSource::Archive),
+            detail,
+        );
+    }
+}
+
+/// Up to `SIMULATION_SAMPLE_SIZE` entries spread across the manifest
+fn sample_entries(manifest: &VaultMetadata) -> Vec<&VaultFileEntry> {
+    let files = &manifest.content.files;
+    let step = files.len().div_ceil(SIMULATION_SAMPLE_SIZE).max(1);
+    files.iter().step_by(step).collect()
+}
+
+/// Sampled entries missing from a decrypted tar.gz payload or differing
+/// from their manifest hash
+///
+/// Tar paths may carry a top-level folder the manifest paths don't.
+fn unmatched_entries(payload: &[u8], sample: &[&VaultFileEntry]) -> io::Result<Vec<String>> {
+    let mut pending: BTreeMap<&str, &str> = sample
+        .iter()
+        .map(|f| (f.path.as_str(), f.sha256.as_str()))
+        .collect();
+    let mut unmatched = Vec::new();
+
+    let mut tar = tar::Archive::new(GzDecoder::new(payload));
+    for entry in tar.entries()? {
+        if pending.is_empty() {
+            break;
+        }
+        let mut entry = entry?;
+        if !entry.header().entry_type().is_file() {
+            continue;
+        }
+        let tar_path = entry.path()?.to_string_lossy().replace('\\', "/");
+        let tar_path = tar_path.trim_start_matches("./");
+        let key = if pending.contains_key(tar_path) {
+            tar_path
+        } else {
+            match tar_path.split_once('/') {
+                Some((_, rest)) if pending.contains_key(rest) => rest,
+                _ => continue,
+            }
+        };
+        let Some((path, expected)) = pending.remove_entry(key) else {
+            continue;
+        };
+
+        let mut hasher = Sha256::new();
+        io::copy(&mut entry, &mut hasher)?;
+        if hex::encode(hasher.finalize()) != expected {
+            unmatched.push(path.to_string());
+        }
+    }
+
+    unmatched.extend(pending.keys().map(|path| path.to_string()));
+    Ok(unmatched)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::crypto::infrastructure::archive_browse::test_archive;
+    use crate::services::file::infrastructure::file_operations::{HashAlgorithm, RawPath};
+    use crate::services::key_management::passphrase::{generate_keypair, wrap_private_key};
+    use crate::services::shared::infrastructure::DeviceInfo;
+    use crate::services::vault::infrastructure::persistence::metadata::RecipientInfo;
+    use chrono::Utc;
+    use std::sync::Mutex;
+
+    const PASSPHRASE: &str = "correct horse battery staple";
+    const FILES: &[(&str, &[u8])] = &[
+        ("will.pdf", b"last will and testament"),
+        ("deeds/house.pdf", b"deed to the house"),
+        ("wallet.txt", b"seed words"),
+    ];
+
+    /// A vault encrypted to one passphrase key, with its kit written out
+    struct Fixture {
+        _temp: TempDir,
+        kit: PathBuf,
+        archive: PathBuf,
+        live: LiveProfile,
+        live_root: PathBuf,
+    }
+
+    impl Fixture {
+        fn new() -> Self {
+            let temp = TempDir::new().unwrap();
+            let kit = temp.path().join("kit");
+            let live_root = temp.path().join("live");
+            fs::create_dir_all(&kit).unwrap();
+
+            let keypair = generate_keypair().unwrap();
+            let key_filename = "estate-key.agekey.enc".to_string();
+            let manifest = manifest(&keypair.public_key.to_string(), &key_filename);
+            let manifest_json = serde_json::to_vec_pretty(&manifest).unwrap();
+
+            let mut entries: Vec<(&str, &[u8])> =
+                vec![("Estate.manifest", manifest_json.as_slice())];
+            entries.extend(FILES.iter().map(|(p, c)| (*p, *c)));
+            let payload = test_archive(&entries);
+            let archive = temp.path().join("Estate-2025-01-13.age");
+            fs::write(
+                &archive,
+                crypto::encrypt_data(&payload, &keypair.public_key).unwrap(),
+            )
+            .unwrap();
+
+            fs::write(
+                kit.join("Estate-RECOVERY.txt"),
+                "BARQLY VAULT RECOVERY GUIDE\n",
+            )
+            .unwrap();
+            fs::write(kit.join("Estate.manifest"), &manifest_json).unwrap();
+            fs::write(
+                kit.join(&key_filename),
+                wrap_private_key(&keypair.private_key, PASSPHRASE).unwrap(),
+            )
+            .unwrap();
+
+            // The live profile has the vault too, so gaps can be traced to it
+            let (keys_dir, manifest_dir) = (live_root.join("keys"), live_root.join("vaults"));
+            fs::create_dir_all(&keys_dir).unwrap();
+            fs::create_dir_all(&manifest_dir).unwrap();
+            fs::copy(kit.join(&key_filename), keys_dir.join(&key_filename)).unwrap();
+            fs::copy(
+                kit.join("Estate.manifest"),
+                manifest_dir.join("Estate.manifest"),
+            )
+            .unwrap();
+            let live = LiveProfile {
+                keys_dir: Some(keys_dir),
+                manifest_dirs: vec![manifest_dir],
+                vaults_dir: Some(live_root.join("Barqly-Vaults")),
+            };
+
+            Self {
+                _temp: temp,
+                kit,
+                archive,
+                live,
+                live_root,
+            }
+        }
+
+        fn simulate(
+            &self,
+            credentials: Option<&RecoveryCredentials>,
+        ) -> (RecoverySimulationReport, Vec<PathBuf>) {
+            let writes = Arc::new(Mutex::new(Vec::new()));
+            let recorded = Arc::clone(&writes);
+            let service = RecoverySimulationService::with_live_profile(self.live.clone())
+                .with_write_hook(Arc::new(move |path: &Path| {
+                    recorded.lock().unwrap().push(path.to_path_buf())
+                }));
+
+            let report = service
+                .simulate(
+                    &self.kit.join("Estate-RECOVERY.txt"),
+                    &self.archive,
+                    credentials,
+                )
+                .unwrap();
+            let writes = writes.lock().unwrap().clone();
+            (report, writes)
+        }
+    }
+
+    fn manifest(public_key: &str, key_filename: &str) -> VaultMetadata {
+        let device_info = DeviceInfo {
+            machine_id: "machine-1".to_string(),
+            machine_label: "laptop".to_string(),
+            created_at: Utc::now(),
+            app_version: "2.0.0".to_string(),
+        };
+        let recipients = vec![RecipientInfo {
+            key_id: "estate-key".to_string(),
+            recipient_type: RecipientType::Passphrase {
+                key_filename: key_filename.to_string(),
+            },
+            public_key: public_key.to_string(),
+            label: "Estate key".to_string(),
+            created_at: Utc::now(),
+        }];
+        let files: Vec<VaultFileEntry> = FILES
+            .iter()
+            .map(|(path, contents)| VaultFileEntry {
+                path: path.to_string(),
+                raw_path: RawPath::from_display(path),
+                lossy_name: false,
+                size: contents.len() as u64,
+                sha256: hex::encode(Sha256::digest(contents)),
+                hash_algorithm: HashAlgorithm::Sha256,
+                digest: None,
+                ownership: None,
+                content_type: None,
+                content_type_mismatch: false,
+                transform: None,
+            })
+            .collect();
+        let total_size = files.iter().map(|f| f.size).sum();
+        VaultMetadata::new(
+            "vault-001".to_string(),
+            "Estate".to_string(),
+            None,
+            "Estate".to_string(),
+            &device_info,
+            None,
+            recipients,
+            files.clone(),
+            files.len(),
+            total_size,
+        )
+    }
+
+    /// Every file under `root` with its contents
+    fn snapshot(root: &Path) -> BTreeMap<PathBuf, Vec<u8>> {
+        let mut files = BTreeMap::new();
+        let mut pending = vec![root.to_path_buf()];
+        while let Some(dir) = pending.pop() {
+            for entry in fs::read_dir(&dir).unwrap().flatten() {
+                let path = entry.path();
+                if path.is_dir() {
+                    pending.push(path);
+                } else {
+                    files.insert(path.clone(), fs::read(&path).unwrap());
+                }
+            }
+        }
+        files
+    }
+
+    fn passphrase() -> RecoveryCredentials {
+        RecoveryCredentials::Passphrase(SecretString::from(PASSPHRASE.to_string()))
+    }
+
+    #[test]
+    fn test_complete_kit_passes() {
+        let fixture = Fixture::new();
+        let (report, _) = fixture.simulate(Some(&passphrase()));
+
+        assert_eq!(report.vault_name, "Estate");
+        assert!(report.is_complete(), "{:#?}", report.steps);
+        assert!(report.decryption_verified());
+        assert_eq!(report.sampled_files, FILES.len());
+        assert!(
+            report
+                .steps
+                .iter()
+                .filter(|s| s.kind != SimulationStepKind::Analysis
+                    && s.kind != SimulationStepKind::TestDecryption)
+                .all(|s| s.source == Some(SimulationSource::Kit))
+        );
+    }
+
+    #[test]
+    fn test_missing_manifest_is_a_gap() {
+        let fixture = Fixture::new();
+        fs::remove_file(fixture.kit.join("Estate.manifest")).unwrap();
+
+        let (report, _) = fixture.simulate(Some(&passphrase()));
+
+        assert!(!report.is_complete());
+        let gaps: Vec<_> = report.gaps().collect();
+        assert_eq!(gaps.len(), 1);
+        assert_eq!(gaps[0].kind, SimulationStepKind::ExternalManifest);
+        assert_eq!(gaps[0].source, Some(SimulationSource::LiveProfile));
+        // The embedded manifest still vouches for the archive's contents
+        assert!(report.decryption_verified());
+    }
+
+    #[test]
+    fn test_wrong_passphrase_fails_unlock() {
+        let fixture = Fixture::new();
+        let wrong = RecoveryCredentials::Passphrase(SecretString::from("nope".to_string()));
+
+        let (report, _) = fixture.simulate(Some(&wrong));
+
+        let unlock = report
+            .steps
+            .iter()
+            .find(|s| s.kind == SimulationStepKind::KeyUnlock)
+            .unwrap();
+        assert_eq!(unlock.status, SimulationStepStatus::Failed);
+        assert!(!report.decryption_verified());
+        assert_eq!(report.gaps().count(), 0);
+    }
+
+    #[test]
+    fn test_sandbox_is_torn_down_and_live_profile_untouched() {
+        let fixture = Fixture::new();
+        let live_before = snapshot(&fixture.live_root);
+        let kit_before = snapshot(&fixture.kit);
+
+        let (report, writes) = fixture.simulate(Some(&passphrase()));
+
+        assert!(report.sandbox_removed);
+        assert!(!writes.is_empty());
+        let sandbox_root = writes[0]
+            .ancestors()
+            .find(|p| {
+                p.file_name().is_some_and(|n| {
+                    n.to_string_lossy()
+                        .starts_with("barqly-recovery-simulation-")
+                })
+            })
+            .unwrap()
+            .to_path_buf();
+        assert!(writes.iter().all(|path| path.starts_with(&sandbox_root)));
+        assert!(!sandbox_root.exists());
+        assert_eq!(snapshot(&fixture.live_root), live_before);
+        assert_eq!(snapshot(&fixture.kit), kit_before);
+    }
+
+    #[test]
+    fn test_sandbox_refuses_writes_outside_its_root() {
+        let outside = TempDir::new().unwrap();
+        let sandbox = RecoverySandbox::create(None).unwrap();
+
+        let escaped = outside.path().join("escaped.txt");
+        assert!(matches!(
+            sandbox.write(&escaped, b"x"),
+            Err(CryptoError::PermissionDenied(_))
+        ));
+        assert!(!escaped.exists());
+        assert!(sandbox.tear_down());
+    }
+
+    #[test]
+    fn test_without_credentials_nothing_is_decrypted() {
+        let fixture = Fixture::new();
+        let (report, _) = fixture.simulate(None);
+
+        assert!(report.is_complete());
+        assert!(!report.decryption_verified());
+        assert_eq!(report.sampled_files, 0);
+    }
+}
@@ -0,0 +1,204 @@
+//! Salvage Decryption Service
+//!
+//! Recovers what it can from an archive damaged by a bad sector or a broken
+//! copy. Chunks that fail authentication are skipped, the undamaged start of
+//! the payload is extracted tolerantly, and every file the manifest lists is
+//! reported as recovered, partially recovered or lost.
+//!
+//! Salvage needs the X25519 identity itself, so only passphrase keys are
+//! supported; a YubiKey never releases its private key.
+
+use crate::prelude::*;
+use crate::services::crypto::domain::{CryptoError, CryptoResult};
+use crate::services::crypto::infrastructure::{self as crypto, ByteRange};
+use crate::services::file::infrastructure::file_operations::{
+    self, SalvagedEntry, archive_entry_name,
+};
+use crate::services::vault::infrastructure::persistence::metadata::VaultMetadata;
+use age::x25519::Identity;
+use std::path::Path;
+use std::str::FromStr;
+
+/// A file recovered in full
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, specta::Type)]
+pub struct RecoveredFile {
+    pub path: String,
+    pub size: u64,
+    /// Content matches the hash in the manifest
+    pub hash_verified: bool,
+    pub written_to: Option<String>,
+}
+
+/// A file cut short by the damage
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, specta::Type)]
+pub struct PartialFile {
+    pub path: String,
+    pub expected_size: u64,
+    pub recovered_bytes: u64,
+    /// Where the recovered start was written (`<name>.partial`)
+    pub written_to: Option<String>,
+}
+
+/// A file the manifest lists that couldn't be recovered at all
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, specta::Type)]
+pub struct LostFile {
+    pub path: String,
+    pub size: u64,
+}
+
+/// Outcome of salvaging (or assessing) a damaged archive
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct SalvageReport {
+    pub recovered: Vec<RecoveredFile>,
+    pub partial: Vec<PartialFile>,
+    pub lost: Vec<LostFile>,
+    /// A manifest listed the archive's files, so `lost` names all of them;
+    /// without one, lost files can't be named
+    pub lost_list_complete: bool,
+    /// Payload byte ranges whose chunks failed authentication
+    pub damaged_ranges: Vec<ByteRange>,
+    pub chunk_count: u64,
+    pub damaged_chunk_count: u64,
+    /// Whether files were written (false for an assessment)
+    pub extracted: bool,
+}
+
+impl SalvageReport {
+    pub fn is_intact(&self) -> bool {
+        self.damaged_chunk_count == 0 && self.partial.is_empty() && self.lost.is_empty()
+    }
+}
+
+/// Service for salvaging damaged archives
+#[derive(Debug)]
+pub struct SalvageDecryptionService;
+
+impl SalvageDecryptionService {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Salvage an age file with an unlocked passphrase key
+    ///
+    /// With `output_dir`, recovered files are written there (truncated ones
+    /// as `<name>.partial`); without it nothing is written and the report
+    /// predicts what a salvage would recover. The embedded manifest names the
+    /// expected files; `fallback_manifest` is used when it didn't survive.
+    #[instrument(skip_all, fields(encrypted_size = encrypted_data.len()))]
+    pub fn salvage(
+        &self,
+        encrypted_data: &[u8],
+        private_key: &crypto::PrivateKey,
+        output_dir: Option<&Path>,
+        fallback_manifest: Option<&VaultMetadata>,
+    ) -> CryptoResult<SalvageReport> {
+        let identity = Identity::from_str(private_key.expose_secret())
+            .map_err(|e| CryptoError::InvalidKey(e.to_string()))?;
+        let payload = crypto::salvage_payload(encrypted_data, &identity)?;
+        if !payload.is_intact() {
+            warn!(
+                damaged_chunks = payload.lost_chunk_count,
+                chunk_count = payload.chunk_count,
+                "Archive has damaged chunks"
+            );
+        }
+
+        if let Some(dir) = output_dir {
+            std::fs::create_dir_all(dir).map_err(|e| CryptoError::IoError(e.to_string()))?;
+        }
+        let salvage = file_operations::salvage_tar_gz(
+            &payload.plaintext[..payload.intact_prefix_len()],
+            output_dir,
+        )
+        .map_err(|e| CryptoError::IoError(e.to_string()))?;
+
+        let embedded = salvage
+            .manifest
+            .as_ref()
+            .and_then(|m| serde_json::from_slice::<VaultMetadata>(&m.contents).ok());
+        let manifest = embedded.as_ref().or(fallback_manifest);
+
+        let (recovered, partial, lost) = classify(&salvage.entries, manifest);
+        info!(
+            recovered = recovered.len(),
+            partial = partial.len(),
+            lost = lost.len(),
+            extracted = output_dir.is_some(),
+            "Archive salvage finished"
+        );
+
+        Ok(SalvageReport {
+            recovered,
+            partial,
+            lost,
+            lost_list_complete: manifest.is_some(),
+            damaged_ranges: payload.lost_ranges,
+            chunk_count: payload.chunk_count,
+            damaged_chunk_count: payload.lost_chunk_count,
+            extracted: output_dir.is_some(),
+        })
+    }
+}
+
+impl Default for SalvageDecryptionService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Sort salvaged user files into recovered and partial, and name the
+/// manifest's files that weren't found as lost
+///
+/// Manifest paths are relative to the selection root, so an entry matches
+/// when its archive path ends with the recorded path, as archived.
+fn classify(
+    entries: &[SalvagedEntry],
+    manifest: Option<&VaultMetadata>,
+) -> (Vec<RecoveredFile>, Vec<PartialFile>, Vec<LostFile>) {
+    let listed = manifest.map_or(&[][..], |m| m.content.files.as_slice());
+    let mut found = vec![false; listed.len()];
+    let mut recovered = Vec::new();
+    let mut partial = Vec::new();
+
+    for entry in entries.iter().filter(|e| !e.internal) {
+        let matched = listed.iter().enumerate().find(|(i, f)| {
+            !found[*i] && entry.path.ends_with(archive_entry_name(&f.relative_path()))
+        });
+        if let Some((i, _)) = matched {
+            found[i] = true;
+        }
+
+        let path = entry.path.to_string_lossy().into_owned();
+        let written_to = entry
+            .written_to
+            .as_ref()
+            .map(|p| p.to_string_lossy().into_owned());
+        if entry.is_complete() {
+            recovered.push(RecoveredFile {
+                path,
+                size: entry.expected_size,
+                hash_verified: matched
+                    .is_some_and(|(_, f)| entry.sha256.as_deref() == Some(f.sha256.as_str())),
+                written_to,
+            });
+        } else {
+            partial.push(PartialFile {
+                path,
+                expected_size: entry.expected_size,
+                recovered_bytes: entry.recovered_bytes,
+                written_to,
+            });
+        }
+    }
+
+    let lost = listed
+        .iter()
+        .zip(&found)
+        .filter(|(_, found)| !**found)
+        .map(|(f, _)| LostFile {
+            path: f.path.clone(),
+            size: f.size,
+        })
+        .collect();
+    (recovered, partial, lost)
+}
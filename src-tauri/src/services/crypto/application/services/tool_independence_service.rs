@@ -0,0 +1,483 @@
+//! Tool Independence Service
+//!
+//! Proves an archive can be recovered without this app. The archive is
+//! decrypted with the age library and nothing but the raw X25519 identity,
+//! the payload is read by a generic tar reader instead of the extraction code
+//! path, and a transcript of the equivalent `age` and `tar` commands is
+//! written for the recovery kit (`<vault>-RECOVERY.txt`).
+//!
+//! Framing outside the age and tar standards (split volumes, parity
+//! sidecars) gets its own steps in the transcript, flagged as depending on
+//! a documented format.
+//!
+//! Like salvage, this needs the identity itself, so only passphrase keys are
+//! supported.
+
+use crate::prelude::*;
+use crate::services::crypto::domain::{CryptoError, CryptoResult};
+use crate::services::crypto::infrastructure::age_armor::ARMOR_BEGIN_LINE;
+use crate::services::file::infrastructure::file_operations::generate_parity_path;
+use age::armor::ArmoredReader;
+use age::x25519::Identity;
+use flate2::read::GzDecoder;
+use std::fs;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+
+/// Heading of the transcript section in the recovery kit
+pub const TRANSCRIPT_HEADING: &str = "RECOVERY WITH STANDARD TOOLS";
+
+const RULE: &str = "───────────────────────────────────────────────";
+
+const GZIP_MAGIC: &[u8] = &[0x1f, 0x8b];
+const ZSTD_MAGIC: &[u8] = &[0x28, 0xb5, 0x2f, 0xfd];
+/// `ustar` magic at offset 257 of a tar header
+const USTAR_MAGIC: &[u8] = b"ustar";
+const USTAR_MAGIC_OFFSET: usize = 257;
+
+/// How the encrypted archive is stored
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, specta::Type)]
+#[serde(rename_all = "snake_case")]
+pub enum ArchiveEncoding {
+    Binary,
+    /// age ASCII armor
+    Armored,
+}
+
+/// Compression of the decrypted tar payload
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, specta::Type)]
+#[serde(rename_all = "snake_case")]
+pub enum PayloadCompression {
+    Gzip,
+    Zstd,
+    /// Plain tar
+    None,
+}
+
+impl PayloadCompression {
+    fn detect(payload: &[u8]) -> Option<Self> {
+        if payload.starts_with(GZIP_MAGIC) {
+            Some(Self::Gzip)
+        } else if payload.starts_with(ZSTD_MAGIC) {
+            Some(Self::Zstd)
+        } else if payload.get(USTAR_MAGIC_OFFSET..USTAR_MAGIC_OFFSET + USTAR_MAGIC.len())
+            == Some(USTAR_MAGIC)
+        {
+            Some(Self::None)
+        } else {
+            None
+        }
+    }
+
+    fn extension(self) -> &'static str {
+        match self {
+            Self::Gzip => "tar.gz",
+            Self::Zstd => "tar.zst",
+            Self::None => "tar",
+        }
+    }
+
+    fn tar_flags(self) -> &'static str {
+        match self {
+            Self::Gzip => "-xzf",
+            Self::Zstd => "--zstd -xf",
+            Self::None => "-xf",
+        }
+    }
+}
+
+/// Framing around the archive that neither age nor tar defines
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, specta::Type)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ProprietaryFraming {
+    /// The archive is stored as numbered volumes to be joined in order
+    SplitVolumes { volumes: Vec<String> },
+    /// Reed–Solomon parity for repairing damage; not needed to decrypt
+    ParitySidecar { file_name: String },
+}
+
+/// Outcome of checking an archive against standard tools
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct ToolIndependenceReport {
+    pub archive_name: String,
+    pub encoding: ArchiveEncoding,
+    /// `#` comment lines precede the armor and must be removed for `age`
+    pub has_preamble: bool,
+    /// Decrypted with the age library and the raw identity alone
+    pub decrypts_with_identity: bool,
+    /// Detected from the decrypted payload; `None` if unrecognized
+    pub compression: Option<PayloadCompression>,
+    /// Every entry was read by a generic tar reader
+    pub tar_readable: bool,
+    pub tar_entry_count: usize,
+    pub framing: Vec<ProprietaryFraming>,
+    /// Why a check failed, if one did
+    pub failure: Option<String>,
+    /// Step-by-step recovery with `age` and `tar`
+    pub transcript: String,
+    /// Recovery kit the transcript was written to (only when every check passed)
+    pub recovery_kit: Option<String>,
+}
+
+impl ToolIndependenceReport {
+    /// Decryption and the tar read both succeeded
+    pub fn is_independent(&self) -> bool {
+        self.decrypts_with_identity && self.tar_readable
+    }
+
+    /// Some step depends on a format this app defines
+    pub fn requires_documented_formats(&self) -> bool {
+        !self.framing.is_empty()
+    }
+}
+
+/// Service for proving archives don't depend on the app
+#[derive(Debug, Default)]
+pub struct ToolIndependenceService;
+
+impl ToolIndependenceService {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Check the archive at `archive_path` with a raw identity
+    ///
+    /// A missing archive is looked for as split volumes (`<archive>.001`,
+    /// `<archive>.002`, …). Failed checks are reported, not returned as
+    /// errors; only an archive that can't be found or read is an error.
+    #[instrument(skip(self, identity))]
+    pub fn check(
+        &self,
+        archive_path: &Path,
+        identity: &Identity,
+    ) -> CryptoResult<ToolIndependenceReport> {
+        let archive_name = file_name(archive_path);
+        let volumes = find_volumes(archive_path);
+
+        let mut framing = Vec::new();
+        let stored = if volumes.is_empty() {
+            fs::read(archive_path).map_err(|e| match e.kind() {
+                io::ErrorKind::NotFound => CryptoError::FileNotFound(archive_name.clone()),
+                _ => CryptoError::IoError(format!("Failed to read {archive_name}: {e}")),
+            })?
+        } else {
+            let mut joined = Vec::new();
+            for volume in &volumes {
+                joined.extend(fs::read(volume).map_err(|e| {
+                    CryptoError::IoError(format!("Failed to read {}: {e}", volume.display()))
+                })?);
+            }
+            framing.push(ProprietaryFraming::SplitVolumes {
+                volumes: volumes.iter().map(|v| file_name(v)).collect(),
+            });
+            joined
+        };
+
+        let parity_path = generate_parity_path(archive_path);
+        if parity_path.is_file() {
+            framing.push(ProprietaryFraming::ParitySidecar {
+                file_name: file_name(&parity_path),
+            });
+        }
+
+        // What `age` would see once the preamble's `#` lines are deleted
+        let has_preamble = stored.first() == Some(&b'#');
+        let age_file = if has_preamble {
+            strip_comment_lines(&stored)
+        } else {
+            stored
+        };
+        let encoding = if age_file.starts_with(ARMOR_BEGIN_LINE.as_bytes()) {
+            ArchiveEncoding::Armored
+        } else {
+            ArchiveEncoding::Binary
+        };
+
+        let mut report = ToolIndependenceReport {
+            archive_name,
+            encoding,
+            has_preamble,
+            decrypts_with_identity: false,
+            compression: None,
+            tar_readable: false,
+            tar_entry_count: 0,
+            framing,
+            failure: None,
+            transcript: String::new(),
+            recovery_kit: None,
+        };
+
+        match decrypt_with_identity(&age_file, identity) {
+            Ok(payload) => {
+                report.decrypts_with_identity = true;
+                report.compression = PayloadCompression::detect(&payload);
+                match report.compression.map(|c| count_tar_entries(&payload, c)) {
+                    Some(Ok(count)) => {
+                        report.tar_readable = true;
+                        report.tar_entry_count = count;
+                    }
+                    Some(Err(e)) => report.failure = Some(e),
+                    None => {
+                        report.failure =
+                            Some("Payload is not a tar, tar.gz or tar.zst stream".to_string())
+                    }
+                }
+            }
+            Err(e) => report.failure = Some(e),
+        }
+
+        report.transcript = render_transcript(&report);
+        info!(
+            archive = %report.archive_name,
+            independent = report.is_independent(),
+            framing = report.framing.len(),
+            "Checked archive against standard tools"
+        );
+        Ok(report)
+    }
+
+    /// Write the transcript into the recovery kit at `kit_path`
+    ///
+    /// A transcript from an earlier check is replaced; the rest of the kit is
+    /// kept. The kit is created if it doesn't exist yet.
+    pub fn append_to_recovery_kit(&self, kit_path: &Path, transcript: &str) -> CryptoResult<()> {
+        let existing = match fs::read_to_string(kit_path) {
+            Ok(existing) => existing,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => String::new(),
+            Err(e) => {
+                return Err(CryptoError::IoError(format!(
+                    "Failed to read recovery kit: {e}"
+                )));
+            }
+        };
+
+        let mut kit = strip_transcript(&existing).trim_end().to_string();
+        if !kit.is_empty() {
+            kit.push_str("\n\n");
+        }
+        kit.push_str(transcript);
+
+        fs::write(kit_path, kit)
+            .map_err(|e| CryptoError::IoError(format!("Failed to write recovery kit: {e}")))?;
+        info!(kit = %kit_path.display(), "Added standard tools transcript to recovery kit");
+        Ok(())
+    }
+}
+
+/// Render the recovery steps for a checked archive
+///
+/// Placeholders in angle brackets stand for what only the user knows.
+pub fn render_transcript(report: &ToolIndependenceReport) -> String {
+    let archive = &report.archive_name;
+    let compression = report.compression.unwrap_or(PayloadCompression::Gzip);
+    let payload = format!("archive.{}", compression.extension());
+
+    let mut text = String::new();
+    text.push_str(&format!("{RULE}\n{TRANSCRIPT_HEADING}\n{RULE}\n\n"));
+    text.push_str(&format!("Archive: {archive}\n"));
+    text.push_str(&format!(
+        "Format: age ({}), {} payload\n",
+        match report.encoding {
+            ArchiveEncoding::Binary => "binary",
+            ArchiveEncoding::Armored => "ASCII armor",
+        },
+        compression.extension()
+    ));
+    if !report.is_independent() {
+        text.push_str(&format!(
+            "WARNING: the check failed ({}). These steps are unverified.\n",
+            report.failure.as_deref().unwrap_or("unknown reason")
+        ));
+    } else if report.requires_documented_formats() {
+        text.push_str("Some steps below depend on a Barqly format; they are marked.\n");
+    } else {
+        text.push_str("Only age and tar are needed.\n");
+    }
+
+    text.push_str("\nYou need:\n");
+    text.push_str("  - age (https://age-encryption.org) or rage\n");
+    text.push_str("  - tar (GNU tar, bsdtar or 7-Zip)\n");
+    text.push_str("  - <KEY_FILE>: your .agekey.enc key file\n");
+    text.push_str("  - <PASSPHRASE>: the passphrase of that key\n");
+    text.push_str("  - <OUTPUT_DIR>: an empty folder for the recovered files\n");
+
+    let mut steps: Vec<String> = Vec::new();
+    let mut age_input = archive.clone();
+
+    for framing in &report.framing {
+        if let ProprietaryFraming::SplitVolumes { volumes } = framing {
+            steps.push(format!(
+                "[BARQLY FORMAT - REQUIRES A DOCUMENTED OPEN FORMAT]\n   \
+                 Join the {} volumes, in order, into one file:\n   cat {} > {archive}",
+                volumes.len(),
+                volumes.join(" ")
+            ));
+        }
+    }
+
+    if report.has_preamble {
+        let stripped = format!("{}.stripped", archive);
+        steps.push(format!(
+            "Remove the readable notes at the top (lines starting with #):\n   \
+             grep -v '^#' {archive} > {stripped}"
+        ));
+        age_input = stripped;
+    }
+
+    steps.push(
+        "Unlock the key file (age asks for <PASSPHRASE>):\n   \
+         age -d -o identity.txt <KEY_FILE>"
+            .to_string(),
+    );
+    steps.push(format!(
+        "Decrypt the archive:\n   age -d -i identity.txt -o {payload} {age_input}"
+    ));
+    steps.push(format!(
+        "Extract the files:\n   mkdir -p <OUTPUT_DIR>\n   tar {} {payload} -C <OUTPUT_DIR>",
+        compression.tar_flags()
+    ));
+    steps.push(format!(
+        "Delete identity.txt and {payload} once the files are safe;\n   \
+         both are unencrypted."
+    ));
+
+    text.push('\n');
+    for (index, step) in steps.iter().enumerate() {
+        text.push_str(&format!("{}. {step}\n\n", index + 1));
+    }
+
+    for framing in &report.framing {
+        if let ProprietaryFraming::ParitySidecar { file_name } = framing {
+            text.push_str(&format!(
+                "[BARQLY FORMAT - REQUIRES A DOCUMENTED OPEN FORMAT]\n\
+                 {file_name} holds Reed-Solomon parity for repairing a damaged\n\
+                 archive. The steps above don't need it; repairing without the\n\
+                 app requires the documented parity format.\n\n"
+            ));
+        }
+    }
+
+    text.push_str(RULE);
+    text.push('\n');
+    text
+}
+
+/// Volumes of a split archive, in order; empty when the archive is whole
+fn find_volumes(archive_path: &Path) -> Vec<PathBuf> {
+    if archive_path.exists() {
+        return Vec::new();
+    }
+    (1..)
+        .map(|n| {
+            let mut name = archive_path.as_os_str().to_os_string();
+            name.push(format!(".{n:03}"));
+            PathBuf::from(name)
+        })
+        .take_while(|volume| volume.is_file())
+        .collect()
+}
+
+fn strip_comment_lines(data: &[u8]) -> Vec<u8> {
+    data.split_inclusive(|&b| b == b'\n')
+        .filter(|line| line.first() != Some(&b'#'))
+        .flatten()
+        .copied()
+        .collect()
+}
+
+/// Decrypt with the age library alone, as the `age` tool would
+fn decrypt_with_identity(age_file: &[u8], identity: &Identity) -> Result<Vec<u8>, String> {
+    let decryptor = age::Decryptor::new(ArmoredReader::new(age_file))
+        .map_err(|e| format!("Not a standard age file: {e}"))?;
+    let mut reader = decryptor
+        .decrypt(std::iter::once(identity as &dyn age::Identity))
+        .map_err(|e| format!("The identity doesn't decrypt the archive: {e}"))?;
+    let mut payload = Vec::new();
+    reader
+        .read_to_end(&mut payload)
+        .map_err(|e| format!("Decryption failed partway: {e}"))?;
+    Ok(payload)
+}
+
+/// Read every entry with a generic tar reader
+fn count_tar_entries(payload: &[u8], compression: PayloadCompression) -> Result<usize, String> {
+    match compression {
+        PayloadCompression::Gzip => read_tar(GzDecoder::new(payload)),
+        PayloadCompression::None => read_tar(payload),
+        PayloadCompression::Zstd => {
+            Err("This build has no zstd reader to check the payload with".to_string())
+        }
+    }
+}
+
+fn read_tar<R: Read>(reader: R) -> Result<usize, String> {
+    let mut archive = tar::Archive::new(reader);
+    let mut count = 0;
+    for entry in archive
+        .entries()
+        .map_err(|e| format!("Not a readable tar stream: {e}"))?
+    {
+        let mut entry = entry.map_err(|e| format!("Unreadable tar entry: {e}"))?;
+        io::copy(&mut entry, &mut io::sink()).map_err(|e| format!("Unreadable tar entry: {e}"))?;
+        count += 1;
+    }
+    Ok(count)
+}
+
+/// The kit without a transcript section written by an earlier check
+fn strip_transcript(kit: &str) -> &str {
+    let heading = format!("{RULE}\n{TRANSCRIPT_HEADING}\n");
+    match kit.find(&heading) {
+        Some(start) => &kit[..start],
+        None => kit,
+    }
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_detects_payload_compression() {
+        assert_eq!(
+            PayloadCompression::detect(&[0x1f, 0x8b, 8]),
+            Some(PayloadCompression::Gzip)
+        );
+        assert_eq!(
+            PayloadCompression::detect(&[0x28, 0xb5, 0x2f, 0xfd, 0]),
+            Some(PayloadCompression::Zstd)
+        );
+        let mut tar = vec![0u8; 512];
+        tar[USTAR_MAGIC_OFFSET..USTAR_MAGIC_OFFSET + 5].copy_from_slice(USTAR_MAGIC);
+        assert_eq!(
+            PayloadCompression::detect(&tar),
+            Some(PayloadCompression::None)
+        );
+        assert_eq!(PayloadCompression::detect(b"hello"), None);
+    }
+
+    #[test]
+    fn test_recovery_kit_transcript_is_replaced() {
+        let temp = TempDir::new().unwrap();
+        let kit = temp.path().join("Vault-RECOVERY.txt");
+        fs::write(&kit, "BARQLY VAULT RECOVERY GUIDE\n").unwrap();
+        let service = ToolIndependenceService::new();
+
+        let first = format!("{RULE}\n{TRANSCRIPT_HEADING}\n{RULE}\n\nfirst\n");
+        let second = format!("{RULE}\n{TRANSCRIPT_HEADING}\n{RULE}\n\nsecond\n");
+        service.append_to_recovery_kit(&kit, &first).unwrap();
+        service.append_to_recovery_kit(&kit, &second).unwrap();
+
+        let contents = fs::read_to_string(&kit).unwrap();
+        assert!(contents.starts_with("BARQLY VAULT RECOVERY GUIDE\n\n"));
+        assert!(contents.ends_with("second\n"));
+        assert!(!contents.contains("first"));
+    }
+}
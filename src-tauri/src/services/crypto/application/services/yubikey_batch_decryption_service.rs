@@ -0,0 +1,663 @@
+//! YubiKey Batch Decryption Service
+//!
+//! Decrypts several archives with one YubiKey unlock. The PIN is entered once
+//! and a single session is reused for every archive. With a cached touch
+//! policy one touch covers archives decrypted within the device's cache
+//! window; when the window lapses mid-batch the user is prompted again.
+//!
+//! Each archive is reported individually under the batch's operation ID. A
+//! failed archive doesn't stop the batch unless `stop_on_error` is set, with
+//! one exception: a rejected PIN always stops it, since every further attempt
+//! would burn another PIN retry.
+
+use super::{ArchiveExtractionService, EmbeddedManifestService, check_app_version};
+use crate::logging::current_trace_id;
+use crate::prelude::*;
+use crate::services::crypto::domain::{CryptoError, CryptoResult};
+use crate::services::crypto::infrastructure as crypto;
+use crate::services::file::infrastructure::file_operations::PathLimitStrategy;
+use crate::services::key_management::shared::KeyEntry;
+use crate::services::key_management::yubikey::infrastructure::pty::age_ops::YubiKeyDecryptSession;
+use crate::services::key_management::yubikey::infrastructure::pty::yubikey_prompt_patterns;
+use crate::services::shared::infrastructure::progress::update_global_progress;
+use crate::types::{DurationMs, IoPriority, ProgressDetails, ProgressUpdate};
+use age::secrecy::{ExposeSecret, SecretString};
+use chrono::Utc;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// How long a YubiKey with cached touch policy remembers a touch
+pub const YUBIKEY_TOUCH_CACHE_WINDOW: Duration = Duration::from_secs(15);
+
+/// A YubiKey unlocked once and reused for several decryptions
+pub trait BatchDecryptSession: Send {
+    fn decrypt(&mut self, encrypted_data: &[u8]) -> CryptoResult<Vec<u8>>;
+}
+
+/// Opens batch sessions for a YubiKey key entry
+pub trait BatchSessionOpener: Send + Sync {
+    fn open(
+        &self,
+        key_entry: &KeyEntry,
+        pin: &SecretString,
+    ) -> CryptoResult<Box<dyn BatchDecryptSession>>;
+}
+
+/// Opener backed by the age CLI over a PTY
+#[derive(Debug, Default)]
+pub struct PtySessionOpener;
+
+impl BatchSessionOpener for PtySessionOpener {
+    fn open(
+        &self,
+        key_entry: &KeyEntry,
+        pin: &SecretString,
+    ) -> CryptoResult<Box<dyn BatchDecryptSession>> {
+        let session = crypto::open_yubikey_decrypt_session(key_entry, pin.expose_secret())
+            .map_err(|e| CryptoError::DecryptionFailed(e.to_string()))?;
+        Ok(Box::new(session))
+    }
+}
+
+impl BatchDecryptSession for YubiKeyDecryptSession {
+    fn decrypt(&mut self, encrypted_data: &[u8]) -> CryptoResult<Vec<u8>> {
+        YubiKeyDecryptSession::decrypt(self, encrypted_data)
+            .map_err(|e| CryptoError::DecryptionFailed(format!("YubiKey decryption failed: {e}")))
+    }
+}
+
+/// Tracks whether the YubiKey still remembers the last touch
+///
+/// The touch is assumed to happen when a decryption starts, which
+/// underestimates the remaining window: an extra prompt is better than a
+/// silent wait for a touch the user wasn't asked for.
+#[derive(Debug, Clone, Copy)]
+pub struct TouchCache {
+    window: Duration,
+    last_touch: Option<Instant>,
+}
+
+impl TouchCache {
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window,
+            last_touch: None,
+        }
+    }
+
+    /// Whether the user must touch the key before the next decryption
+    pub fn needs_touch(&self, now: Instant) -> bool {
+        self.last_touch
+            .is_none_or(|touch| now.saturating_duration_since(touch) >= self.window)
+    }
+
+    pub fn record_touch(&mut self, at: Instant) {
+        self.last_touch = Some(at);
+    }
+}
+
+/// An archive to decrypt and where to extract it
+#[derive(Debug, Clone)]
+pub struct BatchArchiveTarget {
+    pub archive_id: String,
+    pub archive_name: String,
+    pub encrypted_path: PathBuf,
+    pub output_dir: PathBuf,
+}
+
+/// How a batch behaves
+#[derive(Debug, Clone, Copy)]
+pub struct BatchDecryptionOptions {
+    /// Skip the remaining archives after the first failure
+    pub stop_on_error: bool,
+    pub path_limit_strategy: PathLimitStrategy,
+    pub touch_cache_window: Duration,
+}
+
+impl Default for BatchDecryptionOptions {
+    fn default() -> Self {
+        Self {
+            stop_on_error: false,
+            path_limit_strategy: PathLimitStrategy::default(),
+            touch_cache_window: YUBIKEY_TOUCH_CACHE_WINDOW,
+        }
+    }
+}
+
+/// Outcome of one archive in a batch
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+#[serde(rename_all = "snake_case")]
+pub enum BatchArchiveStatus {
+    Decrypted,
+    Failed,
+    /// Not attempted because an earlier archive failed
+    Skipped,
+}
+
+/// Result for one archive in a batch
+#[derive(Debug, Clone, Serialize, Deserialize, specta::Type)]
+pub struct BatchArchiveResult {
+    pub archive_id: String,
+    pub archive_name: String,
+    pub status: BatchArchiveStatus,
+    /// Where the archive was extracted (decrypted archives only)
+    pub output_dir: Option<String>,
+    pub extracted_file_count: usize,
+    /// Whether the user was asked to touch the YubiKey for this archive
+    pub touch_prompted: bool,
+    pub duration_ms: DurationMs,
+    pub error: Option<String>,
+}
+
+/// Results of a batch decryption
+#[derive(Debug, Clone, Serialize, Deserialize, specta::Type)]
+pub struct BatchDecryptionReport {
+    /// Parent operation ID the per-archive progress was reported under
+    pub operation_id: String,
+    /// One entry per requested archive, in request order
+    pub results: Vec<BatchArchiveResult>,
+    pub decrypted: usize,
+    pub failed: usize,
+    pub skipped: usize,
+    /// Touch prompts shown across the whole batch
+    pub touch_prompts: usize,
+}
+
+/// Service for decrypting several archives with one YubiKey session
+pub struct YubiKeyBatchDecryptionService {
+    opener: Arc<dyn BatchSessionOpener>,
+    archive_extraction: ArchiveExtractionService,
+    embedded_manifest: EmbeddedManifestService,
+}
+
+impl std::fmt::Debug for YubiKeyBatchDecryptionService {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("YubiKeyBatchDecryptionService")
+            .finish_non_exhaustive()
+    }
+}
+
+impl YubiKeyBatchDecryptionService {
+    pub fn new() -> Self {
+        Self::with_opener(Arc::new(PtySessionOpener))
+    }
+
+    pub fn with_opener(opener: Arc<dyn BatchSessionOpener>) -> Self {
+        Self {
+            opener,
+            archive_extraction: ArchiveExtractionService::new(),
+            embedded_manifest: EmbeddedManifestService::new(),
+        }
+    }
+
+    /// Decrypt and extract each target in order with a single session
+    #[instrument(skip(self, key_entry, pin, targets), fields(archive_count = targets.len()))]
+    pub fn decrypt_batch(
+        &self,
+        operation_id: &str,
+        key_entry: &KeyEntry,
+        pin: &SecretString,
+        targets: &[BatchArchiveTarget],
+        options: BatchDecryptionOptions,
+    ) -> CryptoResult<BatchDecryptionReport> {
+        if !matches!(key_entry, KeyEntry::Yubikey { .. }) {
+            return Err(CryptoError::InvalidInput(
+                "Batch decryption requires a YubiKey key".to_string(),
+            ));
+        }
+
+        let mut session = self.opener.open(key_entry, pin)?;
+        let mut touch_cache = TouchCache::new(options.touch_cache_window);
+        let mut results = Vec::with_capacity(targets.len());
+        let mut stop_reason: Option<String> = None;
+
+        info!(
+            operation_id,
+            archive_count = targets.len(),
+            stop_on_error = options.stop_on_error,
+            "Starting YubiKey batch decryption"
+        );
+
+        for (index, target) in targets.iter().enumerate() {
+            if let Some(reason) = &stop_reason {
+                let result = skipped(target, reason);
+                report_archive(operation_id, &result, index + 1, targets.len());
+                results.push(result);
+                continue;
+            }
+
+            let started = Instant::now();
+            let touch_prompted = touch_cache.needs_touch(started);
+            let message = if touch_prompted {
+                format!("Touch your YubiKey to decrypt {}", target.archive_name)
+            } else {
+                format!("Decrypting {}", target.archive_name)
+            };
+            report(
+                operation_id,
+                &message,
+                &target.archive_id,
+                index,
+                targets.len(),
+                touch_prompted,
+            );
+
+            let outcome = self.decrypt_one(session.as_mut(), target, options);
+            let duration_ms = DurationMs::from(started.elapsed());
+
+            let result = match outcome {
+                Ok(extracted_file_count) => {
+                    if touch_prompted {
+                        touch_cache.record_touch(started);
+                    }
+                    BatchArchiveResult {
+                        archive_id: target.archive_id.clone(),
+                        archive_name: target.archive_name.clone(),
+                        status: BatchArchiveStatus::Decrypted,
+                        output_dir: Some(target.output_dir.to_string_lossy().to_string()),
+                        extracted_file_count,
+                        touch_prompted,
+                        duration_ms,
+                        error: None,
+                    }
+                }
+                Err(e) => {
+                    let error = e.to_string();
+                    warn!(
+                        operation_id,
+                        archive_id = %target.archive_id,
+                        error = %error,
+                        "Batch archive decryption failed"
+                    );
+                    if yubikey_prompt_patterns::is_pin_failure(&error) {
+                        stop_reason = Some("YubiKey PIN was rejected".to_string());
+                    } else if options.stop_on_error {
+                        stop_reason = Some(format!("Stopped after {} failed", target.archive_name));
+                    }
+                    BatchArchiveResult {
+                        archive_id: target.archive_id.clone(),
+                        archive_name: target.archive_name.clone(),
+                        status: BatchArchiveStatus::Failed,
+                        output_dir: None,
+                        extracted_file_count: 0,
+                        touch_prompted,
+                        duration_ms,
+                        error: Some(error),
+                    }
+                }
+            };
+
+            report_archive(operation_id, &result, index + 1, targets.len());
+            results.push(result);
+        }
+
+        let report = summarize(operation_id, results);
+        info!(
+            operation_id,
+            decrypted = report.decrypted,
+            failed = report.failed,
+            skipped = report.skipped,
+            touch_prompts = report.touch_prompts,
+            "YubiKey batch decryption finished"
+        );
+        Ok(report)
+    }
+
+    /// Decrypt and extract one archive, returning the extracted file count
+    fn decrypt_one(
+        &self,
+        session: &mut dyn BatchDecryptSession,
+        target: &BatchArchiveTarget,
+        options: BatchDecryptionOptions,
+    ) -> CryptoResult<usize> {
+        let encrypted_data = crypto::read_age_archive(&target.encrypted_path).map_err(|e| {
+            CryptoError::FileNotFound(format!("{}: {}", target.encrypted_path.display(), e))
+        })?;
+
+        let decrypted_data = session.decrypt(&encrypted_data)?;
+        if let Some(manifest) = self.embedded_manifest.read_embedded(&decrypted_data)? {
+            check_app_version(&manifest)?;
+        }
+        let extraction = self.archive_extraction.extract_archive(
+            &decrypted_data,
+            &target.output_dir,
+            options.path_limit_strategy,
+        )?;
+
+        Ok(extraction.files.len())
+    }
+}
+
+impl Default for YubiKeyBatchDecryptionService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn skipped(target: &BatchArchiveTarget, reason: &str) -> BatchArchiveResult {
+    BatchArchiveResult {
+        archive_id: target.archive_id.clone(),
+        archive_name: target.archive_name.clone(),
+        status: BatchArchiveStatus::Skipped,
+        output_dir: None,
+        extracted_file_count: 0,
+        touch_prompted: false,
+        duration_ms: DurationMs::default(),
+        error: Some(reason.to_string()),
+    }
+}
+
+fn summarize(operation_id: &str, results: Vec<BatchArchiveResult>) -> BatchDecryptionReport {
+    let count = |status| results.iter().filter(|r| r.status == status).count();
+    BatchDecryptionReport {
+        operation_id: operation_id.to_string(),
+        decrypted: count(BatchArchiveStatus::Decrypted),
+        failed: count(BatchArchiveStatus::Failed),
+        skipped: count(BatchArchiveStatus::Skipped),
+        touch_prompts: results.iter().filter(|r| r.touch_prompted).count(),
+        results,
+    }
+}
+
+fn report_archive(operation_id: &str, result: &BatchArchiveResult, done: usize, total: usize) {
+    let outcome = match result.status {
+        BatchArchiveStatus::Decrypted => "decrypted",
+        BatchArchiveStatus::Failed => "failed",
+        BatchArchiveStatus::Skipped => "skipped",
+    };
+    report(
+        operation_id,
+        &format!("{}: {}", result.archive_name, outcome),
+        &result.archive_id,
+        done,
+        total,
+        false,
+    );
+}
+
+fn report(
+    operation_id: &str,
+    message: &str,
+    archive_id: &str,
+    archives_completed: usize,
+    total_archives: usize,
+    waiting_for_touch: bool,
+) {
+    update_global_progress(
+        operation_id,
+        ProgressUpdate {
+            operation_id: operation_id.to_string(),
+            progress: archives_completed as f32 / total_archives.max(1) as f32,
+            message: message.to_string(),
+            details: Some(ProgressDetails::BatchDecryption {
+                archive_id: archive_id.to_string(),
+                archives_completed,
+                total_archives,
+                waiting_for_touch,
+            }),
+            timestamp: Utc::now(),
+            estimated_time_remaining: None,
+            io_priority: IoPriority::Normal,
+            trace_id: current_trace_id(),
+        },
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::key_management::shared::KeyLifecycleStatus;
+    use std::io::Write;
+    use std::sync::Mutex;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use tempfile::TempDir;
+
+    /// Counts sessions and decryptions; "decrypts" by wrapping the input in a tar.gz
+    #[derive(Default)]
+    struct MockOpener {
+        opened: AtomicUsize,
+        decrypted: Arc<AtomicUsize>,
+        /// Per-decryption delay, to let the touch cache expire mid-batch
+        delay: Duration,
+        /// Inputs that fail to decrypt, with the error to return
+        failures: Vec<(&'static [u8], &'static str)>,
+        pins: Mutex<Vec<String>>,
+    }
+
+    struct MockSession {
+        decrypted: Arc<AtomicUsize>,
+        delay: Duration,
+        failures: Vec<(&'static [u8], &'static str)>,
+    }
+
+    impl BatchSessionOpener for MockOpener {
+        fn open(
+            &self,
+            _key_entry: &KeyEntry,
+            pin: &SecretString,
+        ) -> CryptoResult<Box<dyn BatchDecryptSession>> {
+            self.opened.fetch_add(1, Ordering::SeqCst);
+            self.pins
+                .lock()
+                .unwrap()
+                .push(pin.expose_secret().to_string());
+            Ok(Box::new(MockSession {
+                decrypted: Arc::clone(&self.decrypted),
+                delay: self.delay,
+                failures: self.failures.clone(),
+            }))
+        }
+    }
+
+    impl BatchDecryptSession for MockSession {
+        fn decrypt(&mut self, encrypted_data: &[u8]) -> CryptoResult<Vec<u8>> {
+            std::thread::sleep(self.delay);
+            if let Some((_, error)) = self.failures.iter().find(|(d, _)| *d == encrypted_data) {
+                return Err(CryptoError::DecryptionFailed(error.to_string()));
+            }
+            self.decrypted.fetch_add(1, Ordering::SeqCst);
+            Ok(tar_gz(encrypted_data))
+        }
+    }
+
+    fn tar_gz(content: &[u8]) -> Vec<u8> {
+        let encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::fast());
+        let mut builder = tar::Builder::new(encoder);
+        let mut header = tar::Header::new_gnu();
+        header.set_size(content.len() as u64);
+        header.set_mode(0o644);
+        header.set_cksum();
+        builder
+            .append_data(&mut header, "payload.txt", content)
+            .unwrap();
+        let mut encoder = builder.into_inner().unwrap();
+        encoder.flush().unwrap();
+        encoder.finish().unwrap()
+    }
+
+    fn yubikey_entry() -> KeyEntry {
+        KeyEntry::Yubikey {
+            label: "yubikey-1".to_string(),
+            created_at: Utc::now(),
+            last_used: None,
+            serial: "12345678".to_string(),
+            slot: 1,
+            piv_slot: 82,
+            recipient: "age1yubikey1test".to_string(),
+            identity_tag: "AGE-PLUGIN-YUBIKEY-TEST".to_string(),
+            model: "YubiKey 5".to_string(),
+            firmware_version: None,
+            recovery_code_hash: String::new(),
+            lifecycle_status: KeyLifecycleStatus::Active,
+            status_history: vec![],
+            vault_associations: vec![],
+            deactivated_at: None,
+            previous_lifecycle_status: None,
+            last_reader: None,
+        }
+    }
+
+    /// Five archives whose "ciphertext" is their name
+    fn targets(temp: &TempDir, names: &[&str]) -> Vec<BatchArchiveTarget> {
+        names
+            .iter()
+            .map(|name| {
+                let encrypted_path = temp.path().join(format!("{name}.age"));
+                std::fs::write(&encrypted_path, name.as_bytes()).unwrap();
+                BatchArchiveTarget {
+                    archive_id: format!("id-{name}"),
+                    archive_name: format!("{name}.age"),
+                    encrypted_path,
+                    output_dir: temp.path().join("out").join(name),
+                }
+            })
+            .collect()
+    }
+
+    fn run(
+        opener: Arc<MockOpener>,
+        targets: &[BatchArchiveTarget],
+        options: BatchDecryptionOptions,
+    ) -> BatchDecryptionReport {
+        YubiKeyBatchDecryptionService::with_opener(opener)
+            .decrypt_batch(
+                "batch-test",
+                &yubikey_entry(),
+                &SecretString::from("123456".to_string()),
+                targets,
+                options,
+            )
+            .unwrap()
+    }
+
+    const FIVE: [&str; 5] = ["a1", "a2", "a3", "a4", "a5"];
+
+    #[test]
+    fn test_session_reused_with_single_touch_prompt() {
+        let temp = TempDir::new().unwrap();
+        let targets = targets(&temp, &FIVE);
+        let opener = Arc::new(MockOpener::default());
+
+        let report = run(Arc::clone(&opener), &targets, Default::default());
+
+        assert_eq!(opener.opened.load(Ordering::SeqCst), 1);
+        assert_eq!(opener.pins.lock().unwrap().as_slice(), ["123456"]);
+        assert_eq!(opener.decrypted.load(Ordering::SeqCst), 5);
+        assert_eq!(report.decrypted, 5);
+        assert_eq!(report.touch_prompts, 1);
+        assert!(report.results[0].touch_prompted);
+
+        let extracted = std::fs::read(temp.path().join("out/a3/payload.txt")).unwrap();
+        assert_eq!(extracted, b"a3");
+    }
+
+    #[test]
+    fn test_expired_touch_cache_prompts_again() {
+        let temp = TempDir::new().unwrap();
+        let targets = targets(&temp, &FIVE[..3]);
+        let opener = Arc::new(MockOpener {
+            delay: Duration::from_millis(30),
+            ..Default::default()
+        });
+        let options = BatchDecryptionOptions {
+            touch_cache_window: Duration::from_millis(20),
+            ..Default::default()
+        };
+
+        let report = run(Arc::clone(&opener), &targets, options);
+
+        assert_eq!(opener.opened.load(Ordering::SeqCst), 1);
+        assert_eq!(report.decrypted, 3);
+        assert_eq!(report.touch_prompts, 3);
+    }
+
+    #[test]
+    fn test_failure_does_not_abandon_remaining_archives() {
+        let temp = TempDir::new().unwrap();
+        let targets = targets(&temp, &FIVE);
+        let opener = Arc::new(MockOpener {
+            failures: vec![(b"a2", "corrupted header")],
+            ..Default::default()
+        });
+
+        let report = run(Arc::clone(&opener), &targets, Default::default());
+
+        assert_eq!(opener.opened.load(Ordering::SeqCst), 1);
+        assert_eq!(report.decrypted, 4);
+        assert_eq!(report.failed, 1);
+        assert_eq!(report.results[1].status, BatchArchiveStatus::Failed);
+        assert_eq!(report.results[4].status, BatchArchiveStatus::Decrypted);
+    }
+
+    #[test]
+    fn test_stop_on_error_skips_remaining_archives() {
+        let temp = TempDir::new().unwrap();
+        let targets = targets(&temp, &FIVE);
+        let opener = Arc::new(MockOpener {
+            failures: vec![(b"a2", "corrupted header")],
+            ..Default::default()
+        });
+        let options = BatchDecryptionOptions {
+            stop_on_error: true,
+            ..Default::default()
+        };
+
+        let report = run(Arc::clone(&opener), &targets, options);
+
+        assert_eq!(opener.decrypted.load(Ordering::SeqCst), 1);
+        assert_eq!((report.decrypted, report.failed, report.skipped), (1, 1, 3));
+        assert!(!report.results[2].touch_prompted);
+    }
+
+    #[test]
+    fn test_rejected_pin_always_stops() {
+        let temp = TempDir::new().unwrap();
+        let targets = targets(&temp, &FIVE[..3]);
+        let opener = Arc::new(MockOpener {
+            failures: vec![(b"a1", "Incorrect PIN (2 tries remaining)")],
+            ..Default::default()
+        });
+
+        let report = run(Arc::clone(&opener), &targets, Default::default());
+
+        assert_eq!((report.decrypted, report.failed, report.skipped), (0, 1, 2));
+    }
+
+    #[test]
+    fn test_rejects_non_yubikey_keys() {
+        let entry = KeyEntry::Recipient {
+            label: "friend".to_string(),
+            created_at: Utc::now(),
+            last_used: None,
+            public_key: "age1xyz".to_string(),
+            lifecycle_status: KeyLifecycleStatus::Active,
+            status_history: vec![],
+            vault_associations: vec![],
+            deactivated_at: None,
+            previous_lifecycle_status: None,
+        };
+        let opener = Arc::new(MockOpener::default());
+        let result = YubiKeyBatchDecryptionService::with_opener(opener.clone()).decrypt_batch(
+            "batch-test",
+            &entry,
+            &SecretString::from("123456".to_string()),
+            &[],
+            Default::default(),
+        );
+
+        assert!(matches!(result, Err(CryptoError::InvalidInput(_))));
+        assert_eq!(opener.opened.load(Ordering::SeqCst), 0);
+    }
+
+    #[test]
+    fn test_touch_cache_window() {
+        let start = Instant::now();
+        let mut cache = TouchCache::new(Duration::from_secs(15));
+        assert!(cache.needs_touch(start));
+
+        cache.record_touch(start);
+        assert!(!cache.needs_touch(start + Duration::from_secs(14)));
+        assert!(cache.needs_touch(start + Duration::from_secs(15)));
+    }
+}
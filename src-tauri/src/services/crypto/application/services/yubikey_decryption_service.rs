@@ -1,11 +1,17 @@
 //! YubiKey-based Decryption Service
 //!
 //! Handles decryption using YubiKey hardware tokens via age CLI plugin.
+//! The PIN goes through the YubiKey PIN guard, so a blocked PIN, a PIN that
+//! just failed, or an unconfirmed last attempt never reaches the device.
 
 use crate::prelude::*;
 use crate::services::crypto::domain::{CryptoError, CryptoResult};
 use crate::services::crypto::infrastructure as crypto;
 use crate::services::key_management::shared::KeyEntry;
+use crate::services::key_management::yubikey::application::{PinAttemptGuard, read_counter};
+use crate::services::key_management::yubikey::domain::errors::YubiKeyError;
+use crate::services::key_management::yubikey::domain::models::Serial;
+use crate::services::key_management::yubikey::infrastructure::pty::ykman_ops::get_pin_retries;
 
 /// Service for YubiKey-based decryption operations
 #[derive(Debug)]
@@ -17,29 +23,60 @@ pub fn new() -> Self {
     }
 
     /// Decrypt data using YubiKey via age CLI plugin
+    ///
+    /// With one PIN attempt left the PIN is only sent if `accept_last_attempt` is set.
     #[instrument(skip(self, encrypted_data, key_entry, passphrase))]
     pub fn decrypt_with_yubikey(
         &self,
         encrypted_data: &[u8],
         key_entry: &KeyEntry,
         passphrase: &str,
+        accept_last_attempt: bool,
     ) -> CryptoResult<Vec<u8>> {
         debug!(
             encrypted_data_size = encrypted_data.len(),
             "Starting YubiKey-based decryption via age CLI"
         );
 
+        let serial = match key_entry {
+            KeyEntry::Yubikey { serial, .. } => Serial::new(serial.clone())
+                .map_err(|e| CryptoError::InvalidKey(format!("Invalid YubiKey serial: {}", e)))?,
+            _ => {
+                return Err(CryptoError::DecryptionFailed(
+                    "Expected YubiKey key entry".to_string(),
+                ));
+            }
+        };
+        let read_retries = || {
+            let retries = get_pin_retries(serial.value())
+                .map_err(|e| YubiKeyError::device(format!("PIN retry check failed: {}", e)));
+            read_counter(&serial, retries)
+        };
+
+        let guard = PinAttemptGuard::global();
+        let retries_before = read_retries();
+        guard
+            .check(&serial, passphrase, retries_before, accept_last_attempt)
+            .map_err(CryptoError::PinAttemptRefused)?;
+
         // Use age CLI with YubiKey plugin for decryption
-        let decrypted_data =
-            crypto::decrypt_data_yubikey_cli(encrypted_data, key_entry, passphrase).map_err(
-                |e| {
-                    error!(
-                        error = %e,
-                        "Failed to decrypt data with YubiKey"
-                    );
-                    CryptoError::DecryptionFailed(format!("YubiKey decryption failed: {}", e))
-                },
-            )?;
+        let result = crypto::decrypt_data_yubikey_cli(encrypted_data, key_entry, passphrase);
+        let retries_after = result.is_err().then(read_retries).flatten();
+        guard.record(
+            &serial,
+            passphrase,
+            result.is_ok(),
+            retries_before,
+            retries_after,
+        );
+
+        let decrypted_data = result.map_err(|e| {
+            error!(
+                error = %e,
+                "Failed to decrypt data with YubiKey"
+            );
+            CryptoError::DecryptionFailed(format!("YubiKey decryption failed: {}", e))
+        })?;
 
         info!(
             decrypted_data_size = decrypted_data.len(),
@@ -1,3 +1,5 @@
+use crate::services::key_management::yubikey::domain::errors::YubiKeyError;
+
 #[derive(Debug)]
 pub enum CryptoError {
     EncryptionFailed(String),
@@ -12,6 +14,35 @@ pub enum CryptoError {
     OperationInProgress,
     IoError(String),
     ConfigurationError(String),
+    /// The archive needs a newer app than this one
+    AppTooOld {
+        required: String,
+        current: String,
+    },
+    /// The key file is on a removable drive that isn't connected
+    KeyMediaNotPresent {
+        volume_label: String,
+    },
+    /// Encrypting would write over an archive marked immutable
+    ArchiveImmutable {
+        archive_name: String,
+    },
+    /// The selected key isn't one of the archive's recipients
+    KeyNotARecipient {
+        /// Labels of registered keys that are
+        matching_key_labels: Vec<String>,
+    },
+    /// The selection contains, or is inside, storage the app writes to
+    SelectionOverlapsAppData {
+        paths: Vec<String>,
+    },
+    /// The YubiKey PIN guard refused to send the PIN
+    PinAttemptRefused(YubiKeyError),
+    /// Another vault already has an archive of this name in the output folder
+    CrossVaultNameCollision {
+        archive_name: String,
+        other_vault: String,
+    },
 }
 
 impl std::fmt::Display for CryptoError {
@@ -29,6 +60,52 @@ fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
             Self::OperationInProgress => write!(f, "Another operation is already in progress"),
             Self::IoError(msg) => write!(f, "IO error: {}", msg),
             Self::ConfigurationError(msg) => write!(f, "Configuration error: {}", msg),
+            Self::AppTooOld { required, current } => write!(
+                f,
+                "This archive needs Barqly Vault {} or newer (this app is {}). \
+                 Download the latest version from {}",
+                required,
+                current,
+                crate::constants::APP_DOWNLOAD_URL
+            ),
+            Self::KeyMediaNotPresent { volume_label } => write!(
+                f,
+                "The key file is on the '{}' drive, which isn't connected",
+                volume_label
+            ),
+            Self::ArchiveImmutable { archive_name } => write!(
+                f,
+                "Archive '{}' is marked immutable and can't be replaced",
+                archive_name
+            ),
+            Self::KeyNotARecipient {
+                matching_key_labels,
+            } if matching_key_labels.is_empty() => write!(
+                f,
+                "The selected key can't open this archive, and neither can any other key on \
+                 this device"
+            ),
+            Self::KeyNotARecipient {
+                matching_key_labels,
+            } => write!(
+                f,
+                "The selected key can't open this archive. Try: {}",
+                matching_key_labels.join(", ")
+            ),
+            Self::SelectionOverlapsAppData { paths } => write!(
+                f,
+                "The selection includes Barqly Vault's own storage: {}",
+                paths.join(", ")
+            ),
+            Self::PinAttemptRefused(refusal) => write!(f, "{}", refusal),
+            Self::CrossVaultNameCollision {
+                archive_name,
+                other_vault,
+            } => write!(
+                f,
+                "'{}' in the output folder belongs to vault '{}'",
+                archive_name, other_vault
+            ),
         }
     }
 }
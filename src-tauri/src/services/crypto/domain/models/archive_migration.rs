@@ -0,0 +1,229 @@
+//! Archive parameter migration
+//!
+//! Encryption parameters are the choices an archive was produced with that
+//! may need upgrading later: payload compression, the hash recorded for each
+//! file, and who besides the vault keys it's encrypted to. Migrating
+//! decrypts the vault's current archive and re-encrypts its contents under
+//! new parameters; the new index entry links back to the original.
+//!
+//! Archives are replaced in place, so only the current archive of each name
+//! still exists on disk. Older index entries are counted as superseded.
+
+use super::BenchmarkResult;
+use crate::services::file::infrastructure::file_operations::HashAlgorithm;
+use crate::types::{ByteSize, DurationMs};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Highest gzip compression level
+pub const MAX_GZIP_LEVEL: u32 = 9;
+
+/// Combined decrypt and re-encrypt throughput assumed without a benchmark
+pub const DEFAULT_MIGRATION_BYTES_PER_SECOND: u64 = 20 * 1024 * 1024;
+
+/// Compression of the tar payload
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+#[serde(rename_all = "snake_case")]
+pub enum ArchiveCompression {
+    /// What every archive so far was written with
+    #[default]
+    Gzip,
+    /// Needs `FeatureFlag::ZstdCompression`, which this build doesn't have
+    Zstd,
+}
+
+/// Parameters an archive is produced with
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+pub struct EncryptionParameters {
+    #[serde(default)]
+    pub compression: ArchiveCompression,
+    /// Codec level; the app's default when not given
+    #[serde(default)]
+    pub compression_level: Option<u32>,
+    #[serde(default)]
+    pub hash_algorithm: HashAlgorithm,
+    /// Contacts to encrypt to besides the vault keys; the archive's current
+    /// contacts when not given
+    #[serde(default)]
+    pub contact_ids: Option<Vec<String>>,
+}
+
+impl EncryptionParameters {
+    /// Check the parameters can be written by this build
+    pub fn validate(&self) -> Result<(), String> {
+        match self.compression {
+            ArchiveCompression::Gzip => {
+                if let Some(level) = self.compression_level
+                    && level > MAX_GZIP_LEVEL
+                {
+                    return Err(format!(
+                        "Gzip compression level must be 0-{}, got {}",
+                        MAX_GZIP_LEVEL, level
+                    ));
+                }
+                Ok(())
+            }
+            ArchiveCompression::Zstd => {
+                Err("zstd compression isn't part of this build".to_string())
+            }
+        }
+    }
+}
+
+/// Why an archive doesn't match the target parameters
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, specta::Type)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum MigrationReason {
+    /// Some files are hashed with another algorithm
+    HashAlgorithm {
+        current: Vec<HashAlgorithm>,
+    },
+    /// The manifest predates per-file hash algorithms
+    ManifestSchema {
+        version: Option<u32>,
+    },
+    Compression {
+        current: ArchiveCompression,
+    },
+    /// Contact IDs to add or drop
+    Recipients {
+        added: Vec<String>,
+        removed: Vec<String>,
+    },
+}
+
+/// A current archive that doesn't match the target parameters
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, specta::Type)]
+pub struct ArchiveMigrationNeed {
+    pub archive_id: String,
+    pub archive_name: String,
+    pub created_at: DateTime<Utc>,
+    pub reasons: Vec<MigrationReason>,
+    /// Why it can't be migrated right now, e.g. marked immutable
+    pub blocked_reason: Option<String>,
+    pub archive_size: ByteSize,
+    /// Plaintext size of the archived files
+    pub content_size: ByteSize,
+}
+
+/// Which of a vault's archives need migrating, and what it would take
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, specta::Type)]
+pub struct ParameterMigrationPlan {
+    pub vault_id: String,
+    pub target: EncryptionParameters,
+    pub archives: Vec<ArchiveMigrationNeed>,
+    /// Current archives that already match
+    pub up_to_date: usize,
+    /// Older entries whose files a later encryption replaced
+    pub superseded: usize,
+    pub estimate: MigrationEstimate,
+}
+
+/// Estimated time and space for migrating a set of archives
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, specta::Type)]
+pub struct MigrationEstimate {
+    pub duration: DurationMs,
+    /// Space in use at once while the largest archive migrates: its files
+    /// unpacked for re-encryption plus the new archive staged beside the old
+    pub peak_space: ByteSize,
+    /// Throughput came from a benchmark run on this device
+    pub from_benchmark: bool,
+}
+
+impl MigrationEstimate {
+    /// Estimate for archives given as (archive size, content size)
+    ///
+    /// Each archive is decrypted once and its content re-encrypted once.
+    pub fn new(archives: &[(ByteSize, ByteSize)], bytes_per_second: Option<u64>) -> Self {
+        let rate = bytes_per_second
+            .filter(|rate| *rate > 0)
+            .unwrap_or(DEFAULT_MIGRATION_BYTES_PER_SECOND);
+        let processed: u64 = archives
+            .iter()
+            .map(|(archive, content)| archive.bytes() + content.bytes())
+            .sum();
+        let peak_space = archives
+            .iter()
+            .map(|(archive, content)| archive.bytes() + content.bytes())
+            .max()
+            .unwrap_or_default();
+
+        Self {
+            duration: DurationMs((processed as u128 * 1000 / rate as u128) as u64),
+            peak_space: ByteSize(peak_space),
+            from_benchmark: bytes_per_second.is_some_and(|rate| rate > 0),
+        }
+    }
+}
+
+/// Decrypt and re-encrypt throughput measured by a benchmark, in bytes/second
+pub fn migration_throughput(benchmark: &BenchmarkResult) -> Option<u64> {
+    let millis = benchmark.encrypt_duration_ms.millis() + benchmark.decrypt_duration_ms.millis();
+    (millis > 0).then(|| benchmark.input_size.bytes() * 1000 / millis)
+}
+
+/// Result of migrating one archive
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, specta::Type)]
+pub struct ArchiveMigrationReport {
+    pub original_archive_id: String,
+    /// Index entry of the re-encrypted archive
+    pub archive_id: String,
+    pub encrypted_file_path: String,
+    pub parameters: EncryptionParameters,
+    /// Files checked against the original manifest before re-encrypting
+    pub verified_files: usize,
+    pub original_prune_marked: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parameters_this_build_can_write() {
+        assert!(EncryptionParameters::default().validate().is_ok());
+
+        let level = EncryptionParameters {
+            compression_level: Some(MAX_GZIP_LEVEL + 1),
+            ..EncryptionParameters::default()
+        };
+        assert!(level.validate().is_err());
+
+        let zstd = EncryptionParameters {
+            compression: ArchiveCompression::Zstd,
+            hash_algorithm: HashAlgorithm::Sha512,
+            ..EncryptionParameters::default()
+        };
+        assert!(zstd.validate().unwrap_err().contains("zstd"));
+
+        // Fields left out of a request keep their defaults
+        let parsed: EncryptionParameters =
+            serde_json::from_str(r#"{"hash_algorithm":"sha512"}"#).unwrap();
+        assert_eq!(parsed.compression, ArchiveCompression::Gzip);
+        assert_eq!(parsed.hash_algorithm, HashAlgorithm::Sha512);
+        assert_eq!(parsed.contact_ids, None);
+    }
+
+    #[test]
+    fn test_estimate_from_sizes_and_throughput() {
+        let mb = 1024 * 1024;
+        let archives = [
+            (ByteSize(10 * mb), ByteSize(30 * mb)),
+            (ByteSize(50 * mb), ByteSize(150 * mb)),
+        ];
+
+        let measured = MigrationEstimate::new(&archives, Some(24 * mb));
+        assert_eq!(measured.duration, DurationMs::from_secs(10));
+        assert_eq!(measured.peak_space, ByteSize(200 * mb));
+        assert!(measured.from_benchmark);
+
+        let assumed = MigrationEstimate::new(&archives, None);
+        assert_eq!(assumed.duration, DurationMs::from_secs(12));
+        assert!(!assumed.from_benchmark);
+
+        assert_eq!(
+            MigrationEstimate::new(&[], None),
+            MigrationEstimate::default()
+        );
+    }
+}
@@ -0,0 +1,373 @@
+//! Archive path remapping
+//!
+//! After a source folder is renamed, its archives still hold the old names,
+//! so comparisons with the folder and selective extraction look for paths
+//! that no longer exist. A remap rewrites path prefixes inside an archive:
+//! the archived paths, including the folder the archive is rooted at, are
+//! matched against each mapping's `from_prefix` one component at a time, so
+//! `Tax` never matches `Taxi/`. Where mappings nest, the longest match wins.
+//!
+//! Remapping is planned in full before anything is written, and refused if
+//! it would put two entries at one path or lead outside the archive.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::ffi::{OsStr, OsString};
+use std::path::{Component, Path, PathBuf};
+
+/// Characters a remapped path component can't hold
+const FORBIDDEN_CHARS: [char; 2] = ['\\', '\0'];
+
+/// Rewrite archived paths starting with `from_prefix` to start with `to_prefix`
+///
+/// Prefixes are relative paths with `/` between components, e.g.
+/// `Taxes/2023`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+pub struct PathPrefixMapping {
+    pub from_prefix: String,
+    pub to_prefix: String,
+}
+
+/// The paths of an archive before and after a remap
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PathRemapPlan {
+    /// (archived path, new path) for every path given, in the same order
+    pub moves: Vec<(PathBuf, PathBuf)>,
+    /// Paths a mapping changed
+    pub remapped: usize,
+}
+
+impl PathRemapPlan {
+    /// Paths no mapping matched
+    pub fn unchanged(&self) -> usize {
+        self.moves.len() - self.remapped
+    }
+}
+
+/// Result of remapping one archive's paths
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, specta::Type)]
+pub struct ArchiveRemapReport {
+    pub original_archive_id: String,
+    /// Index entry of the rewritten archive
+    pub archive_id: String,
+    pub encrypted_file_path: String,
+    /// Files and folders given a new path
+    pub remapped_entries: usize,
+    /// Files and folders archived under their old path
+    pub unchanged_entries: usize,
+    /// Files checked against the original manifest before re-encrypting
+    pub verified_files: usize,
+}
+
+/// Plan the new path of each archived path under `mappings`
+///
+/// With `rooted`, the archive holds a single folder and every new path must
+/// stay under one; otherwise it holds loose files, which must stay loose.
+/// Fails if a mapping is malformed or matches nothing, or if two entries
+/// would end up at the same path (ignoring case, as on macOS and Windows)
+/// or a file would end up where a folder is needed.
+pub fn plan_path_remap(
+    paths: &[PathBuf],
+    mappings: &[PathPrefixMapping],
+    rooted: bool,
+) -> Result<PathRemapPlan, String> {
+    if mappings.is_empty() {
+        return Err("Give at least one path mapping".to_string());
+    }
+    let mut parsed = Vec::with_capacity(mappings.len());
+    for mapping in mappings {
+        let from = prefix_components(&mapping.from_prefix)
+            .map_err(|e| format!("From prefix '{}': {}", mapping.from_prefix, e))?;
+        let to = prefix_components(&mapping.to_prefix)
+            .map_err(|e| format!("To prefix '{}': {}", mapping.to_prefix, e))?;
+        if parsed.iter().any(|(other, _, _)| *other == from) {
+            return Err(format!(
+                "'{}' is mapped more than once",
+                mapping.from_prefix
+            ));
+        }
+        parsed.push((from, to, mapping));
+    }
+
+    let mut plan = PathRemapPlan::default();
+    let mut matched = vec![false; parsed.len()];
+    for path in paths {
+        let components: Vec<&OsStr> = path.iter().collect();
+        let best = parsed
+            .iter()
+            .enumerate()
+            .filter(|(_, (from, _, _))| components.starts_with(&as_os_strs(from)))
+            .max_by_key(|(_, (from, _, _))| from.len());
+        let new_path = match best {
+            Some((index, (from, to, _))) => {
+                matched[index] = true;
+                plan.remapped += 1;
+                to.iter()
+                    .map(OsStr::new)
+                    .chain(components[from.len()..].iter().copied())
+                    .collect()
+            }
+            None => path.clone(),
+        };
+        plan.moves.push((path.clone(), new_path));
+    }
+
+    if let Some((_, _, mapping)) = parsed
+        .iter()
+        .zip(&matched)
+        .find_map(|(parsed, matched)| (!matched).then_some(parsed))
+    {
+        return Err(format!(
+            "'{}' doesn't match any path in the archive",
+            mapping.from_prefix
+        ));
+    }
+    check_layout(&plan, rooted)?;
+    check_collisions(&plan)?;
+    Ok(plan)
+}
+
+/// Components of a mapping prefix, rejecting anything that could leave the
+/// archive or name no entry
+fn prefix_components(prefix: &str) -> Result<Vec<String>, String> {
+    let trimmed = prefix.trim_end_matches('/');
+    if trimmed.is_empty() {
+        return Err("can't be empty".to_string());
+    }
+    if trimmed.starts_with('/') || Path::new(trimmed).has_root() {
+        return Err("must be a relative path".to_string());
+    }
+
+    trimmed
+        .split('/')
+        .map(|component| {
+            if component.is_empty() || component == "." || component == ".." {
+                return Err(format!("'{}' isn't allowed as a path component", component));
+            }
+            if component.contains(FORBIDDEN_CHARS) {
+                return Err("can't contain '\\' or NUL".to_string());
+            }
+            // Drive letters and other prefixes on Windows
+            if !matches!(
+                Path::new(component).components().next(),
+                Some(Component::Normal(_))
+            ) {
+                return Err(format!("'{}' isn't allowed as a path component", component));
+            }
+            Ok(component.to_string())
+        })
+        .collect()
+}
+
+fn as_os_strs(components: &[String]) -> Vec<&OsStr> {
+    components.iter().map(OsStr::new).collect()
+}
+
+/// New paths keep the archive's shape: one folder, or loose files
+fn check_layout(plan: &PathRemapPlan, rooted: bool) -> Result<(), String> {
+    let mut roots: HashSet<&OsStr> = HashSet::new();
+    for (old, new) in &plan.moves {
+        let Some(first) = new.iter().next() else {
+            return Err(format!("'{}' would be left without a name", old.display()));
+        };
+        if rooted {
+            roots.insert(first);
+        } else if new.components().count() != 1 {
+            return Err(format!(
+                "'{}' would move into a folder, but this archive holds loose files",
+                old.display()
+            ));
+        }
+    }
+    if roots.len() > 1 {
+        let mut names: Vec<String> = roots
+            .iter()
+            .map(|root| root.to_string_lossy().to_string())
+            .collect();
+        names.sort();
+        return Err(format!(
+            "Every path must stay under one top-level folder, not {}",
+            names.join(", ")
+        ));
+    }
+    Ok(())
+}
+
+/// No two entries share a new path, and no file is the parent of another
+fn check_collisions(plan: &PathRemapPlan) -> Result<(), String> {
+    let mut seen: HashMap<OsString, &Path> = HashMap::new();
+    for (old, new) in &plan.moves {
+        if let Some(other) = seen.insert(fold_case(new), old) {
+            return Err(format!(
+                "'{}' and '{}' would both become '{}'",
+                other.display(),
+                old.display(),
+                new.display()
+            ));
+        }
+    }
+
+    // The folders every entry needs; none of them may be a file. Paths that
+    // are only ever parents were folders before the remap too.
+    let parents: HashMap<OsString, &Path> = plan
+        .moves
+        .iter()
+        .flat_map(|(old, new)| {
+            new.ancestors()
+                .skip(1)
+                .filter(|ancestor| !ancestor.as_os_str().is_empty())
+                .map(move |ancestor| (fold_case(ancestor), old.as_path()))
+        })
+        .collect();
+    for (old, new) in &plan.moves {
+        if let Some(child) = parents.get(&fold_case(new))
+            && is_file(plan, old)
+        {
+            return Err(format!(
+                "'{}' would become '{}', which '{}' needs as a folder",
+                old.display(),
+                new.display(),
+                child.display()
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// A path that no other path lies under
+fn is_file(plan: &PathRemapPlan, path: &Path) -> bool {
+    !plan
+        .moves
+        .iter()
+        .any(|(other, _)| other != path && other.starts_with(path))
+}
+
+fn fold_case(path: &Path) -> OsString {
+    match path.to_str() {
+        Some(path) => path.to_lowercase().into(),
+        None => path.as_os_str().to_os_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mapping(from: &str, to: &str) -> PathPrefixMapping {
+        PathPrefixMapping {
+            from_prefix: from.to_string(),
+            to_prefix: to.to_string(),
+        }
+    }
+
+    fn paths(paths: &[&str]) -> Vec<PathBuf> {
+        paths.iter().map(PathBuf::from).collect()
+    }
+
+    fn new_paths(plan: &PathRemapPlan) -> Vec<String> {
+        plan.moves
+            .iter()
+            .map(|(_, new)| new.to_string_lossy().replace('\\', "/"))
+            .collect()
+    }
+
+    #[test]
+    fn test_nested_prefixes_match_whole_components() {
+        let archived = paths(&[
+            "Taxes",
+            "Taxes/2023",
+            "Taxes/2023/return.pdf",
+            "Taxes/2024/return.pdf",
+            "Taxes/Taxi/receipt.pdf",
+            "Taxes/Tax.txt",
+        ]);
+        let plan = plan_path_remap(
+            &archived,
+            &[
+                mapping("Taxes", "Finanzen"),
+                mapping("Taxes/2023/", "Finanzen/Steuer-2023"),
+                mapping("Taxes/Taxi", "Finanzen/Fahrten"),
+            ],
+            true,
+        )
+        .unwrap();
+
+        assert_eq!(
+            new_paths(&plan),
+            vec![
+                "Finanzen",
+                "Finanzen/Steuer-2023",
+                "Finanzen/Steuer-2023/return.pdf",
+                "Finanzen/2024/return.pdf",
+                "Finanzen/Fahrten/receipt.pdf",
+                "Finanzen/Tax.txt",
+            ]
+        );
+        assert_eq!((plan.remapped, plan.unchanged()), (6, 0));
+
+        // `Tax` is not a prefix of `Taxes` or `Taxi`
+        let error = plan_path_remap(&archived, &[mapping("Taxes/Tax", "Fin")], true).unwrap_err();
+        assert!(error.contains("doesn't match"), "{error}");
+        let plan = plan_path_remap(
+            &archived,
+            &[mapping("Taxes/Tax.txt", "Taxes/Fin.txt")],
+            true,
+        )
+        .unwrap();
+        assert_eq!(new_paths(&plan)[5], "Taxes/Fin.txt");
+        assert_eq!((plan.remapped, plan.unchanged()), (1, 5));
+    }
+
+    #[test]
+    fn test_collisions_and_traversal_are_refused() {
+        let archived = paths(&[
+            "Taxes/2023/return.pdf",
+            "Taxes/2024/return.pdf",
+            "Taxes/notes",
+        ]);
+
+        let error =
+            plan_path_remap(&archived, &[mapping("Taxes/2023", "Taxes/2024")], true).unwrap_err();
+        assert!(error.contains("would both become"), "{error}");
+
+        // Case-only differences collide on macOS and Windows
+        let error = plan_path_remap(
+            &archived,
+            &[mapping("Taxes/2023/return.pdf", "Taxes/2024/RETURN.pdf")],
+            true,
+        )
+        .unwrap_err();
+        assert!(error.contains("would both become"), "{error}");
+
+        // A file can't become the folder another entry needs
+        let error =
+            plan_path_remap(&archived, &[mapping("Taxes/notes", "Taxes/2024")], true).unwrap_err();
+        assert!(error.contains("needs"), "{error}");
+
+        for (from, to) in [
+            ("Taxes", "../Finanzen"),
+            ("Taxes", "Finanzen/./2023"),
+            ("Taxes", "/Finanzen"),
+            ("Taxes", ""),
+            ("Taxes", "Fin\\anzen"),
+            ("../Taxes", "Finanzen"),
+        ] {
+            assert!(
+                plan_path_remap(&archived, &[mapping(from, to)], true).is_err(),
+                "{from} -> {to}"
+            );
+        }
+
+        // The archive's single folder can't be split
+        let error =
+            plan_path_remap(&archived, &[mapping("Taxes/2023", "Archive/2023")], true).unwrap_err();
+        assert!(error.contains("one top-level folder"), "{error}");
+        let error = plan_path_remap(
+            &paths(&["deed.pdf"]),
+            &[mapping("deed.pdf", "docs/deed.pdf")],
+            false,
+        )
+        .unwrap_err();
+        assert!(error.contains("loose files"), "{error}");
+    }
+}
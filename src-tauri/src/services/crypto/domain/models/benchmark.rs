@@ -0,0 +1,165 @@
+//! Throughput benchmark models
+//!
+//! A benchmark pushes synthetic data through the same archive, encrypt,
+//! decrypt and extract steps a real vault uses, and times each step. Results
+//! help judge whether a machine is fast enough for a given backup size.
+
+use super::PhaseTiming;
+use crate::types::{ByteSize, DurationMs};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+const MB: u64 = 1024 * 1024;
+
+/// How much synthetic data to benchmark with
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+pub enum BenchmarkProfile {
+    /// A few seconds on typical hardware
+    Quick,
+    /// Representative of a large backup: many small files plus one big one
+    Thorough,
+}
+
+/// Synthetic data set generated for a run
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+pub struct BenchmarkSizes {
+    pub small_file_count: u32,
+    pub small_file_size: ByteSize,
+    /// Size of the single large file (zero to skip it)
+    pub large_file_size: ByteSize,
+}
+
+impl BenchmarkSizes {
+    pub fn for_profile(profile: BenchmarkProfile) -> Self {
+        match profile {
+            BenchmarkProfile::Quick => Self {
+                small_file_count: 200,
+                small_file_size: ByteSize(64 * 1024),
+                large_file_size: ByteSize(50 * MB),
+            },
+            BenchmarkProfile::Thorough => Self {
+                small_file_count: 1600,
+                small_file_size: ByteSize(64 * 1024),
+                large_file_size: ByteSize(1024 * MB),
+            },
+        }
+    }
+
+    /// Total bytes of synthetic input
+    pub fn total(&self) -> ByteSize {
+        ByteSize(
+            u64::from(self.small_file_count)
+                .saturating_mul(self.small_file_size.0)
+                .saturating_add(self.large_file_size.0),
+        )
+    }
+}
+
+/// Timed step of the pipeline
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+pub enum BenchmarkPhase {
+    /// Copying files into staging and hashing them
+    Hash,
+    /// Building the TAR.GZ archive
+    Compress,
+    /// age encryption of the archive
+    Encrypt,
+    /// age decryption of the archive
+    Decrypt,
+    /// Extracting the archive and checking every file's hash
+    Verify,
+}
+
+/// Throughput of one phase
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, specta::Type)]
+pub struct PhaseThroughput {
+    pub phase: BenchmarkPhase,
+    /// Bytes fed into the phase
+    pub bytes: ByteSize,
+    pub duration_ms: DurationMs,
+    pub megabytes_per_second: f64,
+}
+
+impl PhaseThroughput {
+    pub fn new(phase: BenchmarkPhase, bytes: ByteSize, elapsed: std::time::Duration) -> Self {
+        let secs = elapsed.as_secs_f64();
+        let megabytes_per_second = if secs > 0.0 {
+            bytes.0 as f64 / MB as f64 / secs
+        } else {
+            0.0
+        };
+
+        Self {
+            phase,
+            bytes,
+            duration_ms: elapsed.into(),
+            megabytes_per_second,
+        }
+    }
+
+    /// Throughput of a phase measured by a `PhaseTimer`
+    pub fn from_timing(phase: BenchmarkPhase, timing: &PhaseTiming) -> Self {
+        Self {
+            phase,
+            bytes: timing.bytes,
+            duration_ms: timing.span_ms,
+            megabytes_per_second: timing.megabytes_per_second,
+        }
+    }
+}
+
+/// Outcome of a benchmark run
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, specta::Type)]
+pub struct BenchmarkResult {
+    pub benchmark_id: String,
+    pub profile: BenchmarkProfile,
+    pub sizes: BenchmarkSizes,
+    pub started_at: DateTime<Utc>,
+    pub file_count: u32,
+    pub input_size: ByteSize,
+    pub archive_size: ByteSize,
+    /// In pipeline order
+    pub phases: Vec<PhaseThroughput>,
+    /// Archive and encrypt, as when backing up
+    pub encrypt_duration_ms: DurationMs,
+    /// Decrypt and verify, as when restoring
+    pub decrypt_duration_ms: DurationMs,
+    /// Including data generation and cleanup
+    pub total_duration_ms: DurationMs,
+    /// Process peak resident memory, where the platform reports it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub peak_memory: Option<ByteSize>,
+}
+
+impl BenchmarkResult {
+    pub fn phase(&self, phase: BenchmarkPhase) -> Option<&PhaseThroughput> {
+        self.phases.iter().find(|p| p.phase == phase)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_throughput_in_megabytes_per_second() {
+        let phase = PhaseThroughput::new(
+            BenchmarkPhase::Encrypt,
+            ByteSize(100 * MB),
+            Duration::from_millis(500),
+        );
+        assert_eq!(phase.duration_ms, DurationMs(500));
+        assert!((phase.megabytes_per_second - 200.0).abs() < f64::EPSILON);
+
+        let instant = PhaseThroughput::new(BenchmarkPhase::Hash, ByteSize(1), Duration::ZERO);
+        assert_eq!(instant.megabytes_per_second, 0.0);
+    }
+
+    #[test]
+    fn test_profile_sizes() {
+        let thorough = BenchmarkSizes::for_profile(BenchmarkProfile::Thorough);
+        assert_eq!(thorough.total(), ByteSize(100 * MB + 1024 * MB));
+        assert!(BenchmarkSizes::for_profile(BenchmarkProfile::Quick).total() < thorough.total());
+    }
+}
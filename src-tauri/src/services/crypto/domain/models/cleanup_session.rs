@@ -0,0 +1,97 @@
+//! Time-boxed decryption sessions
+//!
+//! A decryption can ask for its extracted files to be removed after a while.
+//! The session records exactly what was extracted so cleanup only ever
+//! deletes those files; anything the user added or changed since is left
+//! alone.
+
+use crate::services::shared::infrastructure::DeleteHonesty;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// One file written by the extraction
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+pub struct CleanupFile {
+    /// Path relative to the output directory, `/`-separated
+    pub path: String,
+    pub size: u64,
+    /// SHA-256 of the extracted contents
+    pub sha256: String,
+}
+
+/// Extracted tree scheduled for secure deletion
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+pub struct CleanupSession {
+    pub id: String,
+    pub output_dir: String,
+    pub created_at: DateTime<Utc>,
+    pub deadline: DateTime<Utc>,
+    pub files: Vec<CleanupFile>,
+}
+
+/// Pending cleanup as shown to the user
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, specta::Type)]
+pub struct CleanupSessionSummary {
+    pub id: String,
+    pub output_dir: String,
+    pub created_at: DateTime<Utc>,
+    pub deadline: DateTime<Utc>,
+    pub file_count: usize,
+}
+
+impl From<&CleanupSession> for CleanupSessionSummary {
+    fn from(session: &CleanupSession) -> Self {
+        Self {
+            id: session.id.clone(),
+            output_dir: session.output_dir.clone(),
+            created_at: session.created_at,
+            deadline: session.deadline,
+            file_count: session.files.len(),
+        }
+    }
+}
+
+/// Why a due cleanup left the directory in place
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, specta::Type)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum CleanupRefusal {
+    /// The output directory is no longer where it was extracted
+    DirectoryMoved,
+    /// Files that weren't extracted now sit in the directory
+    ForeignFiles { paths: Vec<String> },
+    /// Extracted files were edited since
+    ModifiedFiles { paths: Vec<String> },
+}
+
+/// What happened to a due session
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, specta::Type)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum CleanupOutcome {
+    /// Extracted files were deleted
+    Deleted {
+        files_removed: usize,
+        /// Files that couldn't be removed
+        failures: Vec<String>,
+        /// How far the deletion can be trusted on this storage
+        honesty: DeleteHonesty,
+    },
+    /// Nothing was touched; the user is warned instead
+    Refused { reason: CleanupRefusal },
+}
+
+/// Result of a due session, sent as a notification
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, specta::Type)]
+pub struct CleanupResult {
+    pub session_id: String,
+    pub output_dir: String,
+    pub outcome: CleanupOutcome,
+}
+
+impl CleanupResult {
+    pub fn is_warning(&self) -> bool {
+        match &self.outcome {
+            CleanupOutcome::Deleted { failures, .. } => !failures.is_empty(),
+            CleanupOutcome::Refused { .. } => true,
+        }
+    }
+}
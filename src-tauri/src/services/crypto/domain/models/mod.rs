@@ -1,3 +1,15 @@
+pub mod archive_migration;
+pub mod archive_path_remap;
+pub mod benchmark;
+pub mod cleanup_session;
 pub mod crypto_rules;
+pub mod panic_lock;
+pub mod timing;
 
+pub use archive_migration::*;
+pub use archive_path_remap::*;
+pub use benchmark::*;
+pub use cleanup_session::*;
 pub use crypto_rules::*;
+pub use panic_lock::*;
+pub use timing::*;
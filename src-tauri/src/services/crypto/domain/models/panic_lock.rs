@@ -0,0 +1,86 @@
+//! Panic lock
+//!
+//! Wipes the sensitive state the app holds outside the vaults in one step:
+//! decrypted browse snapshots, YubiKey PTY sessions, recovery shares held
+//! for copying, the app's copy on the clipboard and decrypted output
+//! awaiting scheduled cleanup. Vault data and keys are never touched.
+
+use crate::services::crypto::domain::models::CleanupResult;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+/// Text the user types to confirm a panic lock
+pub const PANIC_LOCK_CONFIRMATION: &str = "LOCK";
+
+/// What a panic lock would clear right now, shown before confirming
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, specta::Type)]
+pub struct PanicLockPreview {
+    /// Running operations that would be asked to stop
+    pub running_operations: Vec<String>,
+    pub browse_sessions: usize,
+    pub pty_sessions: usize,
+    /// Vaults whose new recovery shares are still held for copying
+    pub recovery_share_sets: usize,
+    /// Whether a copy from this app is waiting to be cleared
+    pub clipboard_copy: bool,
+    /// Decrypted output scheduled for deletion, which would be deleted now
+    pub cleanup_sessions: usize,
+}
+
+/// A step of the panic lock that didn't fully succeed
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, specta::Type)]
+pub struct PanicLockFailure {
+    pub step: PanicLockStep,
+    pub message: String,
+}
+
+/// Steps of a panic lock, in the order they run
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, specta::Type)]
+#[serde(rename_all = "snake_case")]
+pub enum PanicLockStep {
+    CancelOperations,
+    CloseBrowseSessions,
+    KillPtySessions,
+    ForgetRecoveryShares,
+    ClearClipboard,
+    RunCleanups,
+    RecordAudit,
+}
+
+/// What a panic lock cleared
+///
+/// Every step runs even when an earlier one fails; `failures` lists what
+/// went wrong. A second panic lock clears nothing and reports so.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, specta::Type)]
+pub struct PanicLockReport {
+    pub locked_at: DateTime<Utc>,
+    /// Operations asked to stop; they wind down on their own
+    pub cancelled_operations: Vec<String>,
+    /// Browse sessions closed, their snapshots zeroized
+    pub browse_sessions_closed: usize,
+    pub pty_sessions_killed: usize,
+    /// Vaults whose held recovery shares were zeroized
+    pub recovery_share_sets_forgotten: usize,
+    /// Whether the app's copy was cleared from the clipboard
+    pub clipboard_cleared: bool,
+    /// Outcome of each pending cleanup, run immediately
+    pub cleanups: Vec<CleanupResult>,
+    pub failures: Vec<PanicLockFailure>,
+}
+
+impl PanicLockReport {
+    /// Whether nothing was left behind
+    pub fn is_complete(&self) -> bool {
+        self.failures.is_empty() && self.cleanups.iter().all(|c| !c.is_warning())
+    }
+
+    /// Whether anything was cleared
+    pub fn cleared_anything(&self) -> bool {
+        !self.cancelled_operations.is_empty()
+            || self.browse_sessions_closed > 0
+            || self.pty_sessions_killed > 0
+            || self.recovery_share_sets_forgotten > 0
+            || self.clipboard_cleared
+            || !self.cleanups.is_empty()
+    }
+}
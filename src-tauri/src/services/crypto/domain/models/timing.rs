@@ -0,0 +1,421 @@
+//! Per-operation timing breakdown
+//!
+//! Encryption and decryption record how long each pipeline phase took and
+//! how many bytes it handled, so a slow operation can be explained from its
+//! result instead of from logs. The clock is read only when a phase begins or
+//! ends.
+//!
+//! Phases may overlap (hashing running beside archiving). Each phase then has
+//! a span, its own start to end, and an exclusive estimate where overlapping
+//! time is shared evenly between the phases running. Exclusive times plus the
+//! time outside every phase add up to the total.
+//!
+//! The breakdown also counts the files hashed while the operation ran by how
+//! they were read (memory-mapped or buffered). The counters are process-wide,
+//! so an operation running alongside adds its reads too.
+
+use crate::prelude::*;
+use crate::services::file::infrastructure::file_operations::HashReadCounts;
+use crate::types::{ByteSize, DurationMs};
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+
+const MB: f64 = 1024.0 * 1024.0;
+
+/// Timed step of an encryption or decryption
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, specta::Type)]
+#[serde(rename_all = "snake_case")]
+pub enum OperationPhase {
+    /// Checking the selection, loading the vault or key
+    Validation,
+    /// Hashing source files for the manifest
+    Hashing,
+    /// Staging files and building the archive
+    Archiving,
+    /// age encryption of the archive
+    Encryption,
+    /// Reading the encrypted archive
+    Read,
+    /// age decryption of the archive
+    Decryption,
+    /// Writing archive entries to the output directory
+    Extraction,
+    /// Checking restored files against the manifest
+    Verification,
+    /// Writing bundles, manifests and keys
+    Finalize,
+}
+
+impl OperationPhase {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Validation => "validation",
+            Self::Hashing => "hashing",
+            Self::Archiving => "archiving",
+            Self::Encryption => "encryption",
+            Self::Read => "read",
+            Self::Decryption => "decryption",
+            Self::Extraction => "extraction",
+            Self::Verification => "verification",
+            Self::Finalize => "finalize",
+        }
+    }
+}
+
+/// Time and bytes of one phase
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, specta::Type)]
+pub struct PhaseTiming {
+    pub phase: OperationPhase,
+    /// When the phase first began, from the start of the operation
+    pub offset_ms: DurationMs,
+    /// Wall time from start to end (summed if the phase ran more than once)
+    pub span_ms: DurationMs,
+    /// Span less the share of time other phases ran alongside it
+    pub exclusive_ms: DurationMs,
+    /// Bytes fed into the phase
+    pub bytes: ByteSize,
+    /// Bytes over span; zero when nothing was measured
+    pub megabytes_per_second: f64,
+}
+
+/// Phase timings of a whole operation
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, specta::Type)]
+pub struct TimingBreakdown {
+    /// In the order the phases began
+    pub phases: Vec<PhaseTiming>,
+    pub total_ms: DurationMs,
+    /// Time not inside any phase
+    pub unaccounted_ms: DurationMs,
+    /// Files hashed during the operation, by how they were read
+    #[serde(default)]
+    pub hash_reads: HashReadCounts,
+}
+
+impl TimingBreakdown {
+    pub fn phase(&self, phase: OperationPhase) -> Option<&PhaseTiming> {
+        self.phases.iter().find(|p| p.phase == phase)
+    }
+
+    /// Log each phase with structured fields
+    pub fn log(&self, operation: &'static str) {
+        for phase in &self.phases {
+            info!(
+                operation,
+                phase = phase.phase.as_str(),
+                span_ms = phase.span_ms.millis(),
+                exclusive_ms = phase.exclusive_ms.millis(),
+                bytes = phase.bytes.bytes(),
+                megabytes_per_second = phase.megabytes_per_second,
+                "Phase timing"
+            );
+        }
+        info!(
+            operation,
+            total_ms = self.total_ms.millis(),
+            unaccounted_ms = self.unaccounted_ms.millis(),
+            "Operation timing"
+        );
+        if self.hash_reads != HashReadCounts::default() {
+            info!(
+                operation,
+                mapped = self.hash_reads.mapped,
+                buffered = self.hash_reads.buffered,
+                fallbacks = self.hash_reads.fallbacks,
+                "Hash reads"
+            );
+        }
+    }
+}
+
+/// Handle of a phase started with [`PhaseTimer::begin`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PhaseId(usize);
+
+#[derive(Debug)]
+struct Interval {
+    phase: OperationPhase,
+    start: Duration,
+    end: Option<Duration>,
+    bytes: u64,
+}
+
+/// Collects phase boundaries while an operation runs
+#[derive(Debug)]
+pub struct PhaseTimer {
+    origin: Instant,
+    intervals: Vec<Interval>,
+    hash_reads_at_start: HashReadCounts,
+}
+
+impl PhaseTimer {
+    pub fn start() -> Self {
+        Self {
+            origin: Instant::now(),
+            intervals: Vec::new(),
+            hash_reads_at_start: HashReadCounts::current(),
+        }
+    }
+
+    /// Start a phase; several may be open at once
+    pub fn begin(&mut self, phase: OperationPhase) -> PhaseId {
+        self.intervals.push(Interval {
+            phase,
+            start: self.origin.elapsed(),
+            end: None,
+            bytes: 0,
+        });
+        PhaseId(self.intervals.len() - 1)
+    }
+
+    /// End a phase and credit it with the bytes it handled
+    pub fn end(&mut self, id: PhaseId, bytes: u64) {
+        let now = self.origin.elapsed();
+        if let Some(interval) = self.intervals.get_mut(id.0)
+            && interval.end.is_none()
+        {
+            interval.end = Some(now);
+            interval.bytes = bytes;
+        }
+    }
+
+    /// Record a phase timed elsewhere, such as on a worker thread
+    pub fn record(&mut self, phase: OperationPhase, started: Instant, ended: Instant, bytes: u64) {
+        let start = started.saturating_duration_since(self.origin);
+        self.intervals.push(Interval {
+            phase,
+            start,
+            end: Some(ended.saturating_duration_since(self.origin).max(start)),
+            bytes,
+        });
+    }
+
+    /// Close any open phases and build the breakdown
+    pub fn finish(self) -> TimingBreakdown {
+        let total = self.origin.elapsed();
+        let intervals: Vec<(OperationPhase, Duration, Duration, u64)> = self
+            .intervals
+            .iter()
+            .map(|i| (i.phase, i.start, i.end.unwrap_or(total).min(total), i.bytes))
+            .collect();
+
+        let (exclusive, unaccounted) = exclusive_shares(&intervals, total);
+
+        let mut phases: Vec<(OperationPhase, Duration, Duration, Duration, u64)> = Vec::new();
+        for (index, &(phase, start, end, bytes)) in intervals.iter().enumerate() {
+            let span = end.saturating_sub(start);
+            match phases.iter_mut().find(|p| p.0 == phase) {
+                Some(entry) => {
+                    entry.1 = entry.1.min(start);
+                    entry.2 += span;
+                    entry.3 += exclusive[index];
+                    entry.4 = entry.4.saturating_add(bytes);
+                }
+                None => phases.push((phase, start, span, exclusive[index], bytes)),
+            }
+        }
+        phases.sort_by_key(|p| p.1);
+
+        TimingBreakdown {
+            phases: phases
+                .into_iter()
+                .map(|(phase, offset, span, exclusive, bytes)| {
+                    let secs = span.as_secs_f64();
+                    PhaseTiming {
+                        phase,
+                        offset_ms: offset.into(),
+                        span_ms: span.into(),
+                        exclusive_ms: exclusive.into(),
+                        bytes: ByteSize(bytes),
+                        megabytes_per_second: if secs > 0.0 {
+                            bytes as f64 / MB / secs
+                        } else {
+                            0.0
+                        },
+                    }
+                })
+                .collect(),
+            total_ms: total.into(),
+            unaccounted_ms: unaccounted.into(),
+            hash_reads: HashReadCounts::current().since(self.hash_reads_at_start),
+        }
+    }
+}
+
+/// Split the timeline at every boundary and share each slice between the
+/// intervals covering it; slices nothing covers are unaccounted
+fn exclusive_shares(
+    intervals: &[(OperationPhase, Duration, Duration, u64)],
+    total: Duration,
+) -> (Vec<Duration>, Duration) {
+    let mut bounds: Vec<Duration> = intervals
+        .iter()
+        .flat_map(|&(_, start, end, _)| [start, end])
+        .chain([Duration::ZERO, total])
+        .collect();
+    bounds.sort();
+    bounds.dedup();
+
+    let mut exclusive = vec![Duration::ZERO; intervals.len()];
+    let mut unaccounted = Duration::ZERO;
+    for slice in bounds.windows(2) {
+        let (from, to) = (slice[0], slice[1]);
+        let active: Vec<usize> = intervals
+            .iter()
+            .enumerate()
+            .filter(|(_, (_, start, end, _))| *start <= from && *end >= to && end > start)
+            .map(|(index, _)| index)
+            .collect();
+        if active.is_empty() {
+            unaccounted += to - from;
+            continue;
+        }
+        let share = (to - from) / active.len() as u32;
+        for index in active {
+            exclusive[index] += share;
+        }
+    }
+    (exclusive, unaccounted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread::sleep;
+
+    const STEP: Duration = Duration::from_millis(30);
+
+    fn millis(d: DurationMs) -> i64 {
+        d.millis() as i64
+    }
+
+    #[test]
+    fn test_sequential_phases() {
+        let mut timer = PhaseTimer::start();
+        for (phase, bytes) in [
+            (OperationPhase::Validation, 0),
+            (OperationPhase::Hashing, 4096),
+            (OperationPhase::Archiving, 4096),
+            (OperationPhase::Encryption, 1024),
+        ] {
+            let id = timer.begin(phase);
+            sleep(STEP);
+            timer.end(id, bytes);
+        }
+        sleep(STEP);
+        let breakdown = timer.finish();
+
+        let order: Vec<_> = breakdown.phases.iter().map(|p| p.phase).collect();
+        assert_eq!(
+            order,
+            vec![
+                OperationPhase::Validation,
+                OperationPhase::Hashing,
+                OperationPhase::Archiving,
+                OperationPhase::Encryption,
+            ]
+        );
+        for phase in &breakdown.phases {
+            assert!(phase.span_ms >= DurationMs(30));
+            assert_eq!(phase.exclusive_ms, phase.span_ms);
+        }
+        assert_eq!(
+            breakdown.phase(OperationPhase::Hashing).unwrap().bytes,
+            ByteSize(4096)
+        );
+        assert_eq!(
+            breakdown.phase(OperationPhase::Encryption).unwrap().bytes,
+            ByteSize(1024)
+        );
+        assert_eq!(
+            breakdown
+                .phase(OperationPhase::Validation)
+                .unwrap()
+                .megabytes_per_second,
+            0.0
+        );
+        assert!(breakdown.unaccounted_ms >= DurationMs(30));
+
+        let accounted: i64 = breakdown
+            .phases
+            .iter()
+            .map(|p| millis(p.exclusive_ms))
+            .sum();
+        let sum = accounted + millis(breakdown.unaccounted_ms);
+        assert!((sum - millis(breakdown.total_ms)).abs() <= 5);
+    }
+
+    #[test]
+    fn test_overlapping_phases_share_time() {
+        let mut timer = PhaseTimer::start();
+        let hashing = timer.begin(OperationPhase::Hashing);
+        sleep(STEP);
+        let archiving = timer.begin(OperationPhase::Archiving);
+        sleep(STEP * 2);
+        timer.end(hashing, 2048);
+        sleep(STEP);
+        timer.end(archiving, 8192);
+        let breakdown = timer.finish();
+
+        let hashing = breakdown.phase(OperationPhase::Hashing).unwrap();
+        let archiving = breakdown.phase(OperationPhase::Archiving).unwrap();
+        assert!(hashing.offset_ms < archiving.offset_ms);
+        assert!(hashing.exclusive_ms < hashing.span_ms);
+        assert!(archiving.exclusive_ms < archiving.span_ms);
+        assert_eq!(hashing.bytes, ByteSize(2048));
+        assert_eq!(archiving.bytes, ByteSize(8192));
+
+        // Spans double-count the overlap; exclusive times don't
+        let spans = millis(hashing.span_ms) + millis(archiving.span_ms);
+        let exclusive = millis(hashing.exclusive_ms) + millis(archiving.exclusive_ms);
+        assert!(spans > millis(breakdown.total_ms));
+        let sum = exclusive + millis(breakdown.unaccounted_ms);
+        assert!((sum - millis(breakdown.total_ms)).abs() <= 5);
+    }
+
+    #[test]
+    fn test_repeated_phase_is_merged() {
+        let mut timer = PhaseTimer::start();
+        for bytes in [100, 200] {
+            let id = timer.begin(OperationPhase::Archiving);
+            sleep(STEP);
+            timer.end(id, bytes);
+        }
+        let started = Instant::now();
+        sleep(STEP);
+        timer.record(OperationPhase::Encryption, started, Instant::now(), 50);
+        let open = timer.begin(OperationPhase::Finalize);
+        sleep(STEP);
+        timer.end(open, 10);
+        timer.end(open, 99);
+        let breakdown = timer.finish();
+
+        assert_eq!(breakdown.phases.len(), 3);
+        let archiving = breakdown.phase(OperationPhase::Archiving).unwrap();
+        assert_eq!(archiving.bytes, ByteSize(300));
+        assert!(archiving.span_ms >= DurationMs(60));
+        assert_eq!(
+            breakdown.phase(OperationPhase::Encryption).unwrap().bytes,
+            ByteSize(50)
+        );
+        assert_eq!(
+            breakdown.phase(OperationPhase::Finalize).unwrap().bytes,
+            ByteSize(10)
+        );
+    }
+
+    #[test]
+    fn test_hash_reads_are_counted() {
+        use crate::services::file::infrastructure::file_operations::{
+            HashAlgorithm, calculate_file_hash_with,
+        };
+
+        let temp = tempfile::TempDir::new().unwrap();
+        let path = temp.path().join("input.bin");
+        std::fs::write(&path, b"hash me").unwrap();
+
+        let timer = PhaseTimer::start();
+        calculate_file_hash_with(&path, HashAlgorithm::Sha256).unwrap();
+        let breakdown = timer.finish();
+        assert!(breakdown.hash_reads.buffered >= 1);
+    }
+}
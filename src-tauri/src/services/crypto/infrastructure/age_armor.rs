@@ -0,0 +1,287 @@
+//! ASCII-armored archives with a readable preamble
+//!
+//! An armored archive is standard age armor preceded by a few `# key: value`
+//! comment lines, so someone who finds the file can tell what made it and
+//! where the recovery instructions are without a key:
+//!
+//! ```text
+//! # Barqly Vault encrypted archive
+//! # app: Barqly Vault
+//! # created: 2025-01-13
+//! # recovery: https://barqly.com/recovery
+//! # manifest: barqly.vault.manifest/3
+//! # To decrypt with the age command-line tool, first delete the lines starting with #
+//! -----BEGIN AGE ENCRYPTED FILE-----
+//! ```
+//!
+//! Nothing in the preamble is secret; the vault name is only written when the
+//! user opts in. age accepts nothing before the armor's first line, hence the
+//! last comment line. Readers in this app skip the preamble and accept armored
+//! and binary archives alike.
+
+use age::armor::{ArmoredReader, ArmoredWriter, Format};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::path::Path;
+
+/// First line of age's ASCII armor
+pub const ARMOR_BEGIN_LINE: &str = "-----BEGIN AGE ENCRYPTED FILE-----";
+
+/// App named in the preamble of archives written here
+pub const PREAMBLE_APP_NAME: &str = "Barqly Vault";
+
+/// Where the preamble points for recovery instructions
+pub const RECOVERY_INSTRUCTIONS_URL: &str = "https://barqly.com/recovery";
+
+/// Preambles longer than this are treated as malformed
+pub const MAX_PREAMBLE_LEN: usize = 4096;
+
+/// Longest value written for a single preamble field
+const MAX_PREAMBLE_VALUE_CHARS: usize = 120;
+
+const AGE_CLI_HINT: &str =
+    "# To decrypt with the age command-line tool, first delete the lines starting with #";
+
+/// Request for ASCII-armored output with a readable preamble
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+pub struct ArmoredOutputOptions {
+    /// Also name the vault in the preamble (off by default: the preamble is
+    /// readable by anyone who has the file)
+    #[serde(default)]
+    pub include_vault_name: bool,
+}
+
+/// Non-secret notes at the top of an armored archive
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, specta::Type)]
+pub struct ArchivePreamble {
+    /// App that wrote the archive
+    pub app: String,
+    /// Vault display name, only present when the user opted in
+    pub vault_name: Option<String>,
+    /// Creation date (YYYY-MM-DD)
+    pub created: Option<String>,
+    /// Where to find recovery instructions
+    pub recovery_url: Option<String>,
+    /// Manifest schema of the vault, e.g. "barqly.vault.manifest/3"
+    pub manifest_schema: Option<String>,
+}
+
+impl ArchivePreamble {
+    /// Preamble for an archive written by this app now
+    pub fn new(created: DateTime<Utc>, manifest_schema: &str, vault_name: Option<&str>) -> Self {
+        Self {
+            app: PREAMBLE_APP_NAME.to_string(),
+            vault_name: vault_name.map(str::to_string),
+            created: Some(created.format("%Y-%m-%d").to_string()),
+            recovery_url: Some(RECOVERY_INSTRUCTIONS_URL.to_string()),
+            manifest_schema: Some(manifest_schema.to_string()),
+        }
+    }
+
+    /// The comment lines written ahead of the armor
+    pub fn render(&self) -> String {
+        let mut out = format!("# {} encrypted archive\n", clean_value(&self.app));
+        let fields = [
+            ("app", Some(&self.app)),
+            ("vault", self.vault_name.as_ref()),
+            ("created", self.created.as_ref()),
+            ("recovery", self.recovery_url.as_ref()),
+            ("manifest", self.manifest_schema.as_ref()),
+        ];
+        for (key, value) in fields {
+            if let Some(value) = value {
+                out.push_str(&format!("# {}: {}\n", key, clean_value(value)));
+            }
+        }
+        out.push_str(AGE_CLI_HINT);
+        out.push('\n');
+        out
+    }
+
+    fn parse(lines: &[String]) -> Option<Self> {
+        let mut fields = HashMap::new();
+        for line in lines {
+            let Some((key, value)) = line.trim_start_matches('#').trim().split_once(": ") else {
+                continue;
+            };
+            fields
+                .entry(key.trim().to_ascii_lowercase())
+                .or_insert_with(|| value.trim().to_string());
+        }
+        Some(Self {
+            app: fields.remove("app")?,
+            vault_name: fields.remove("vault"),
+            created: fields.remove("created"),
+            recovery_url: fields.remove("recovery"),
+            manifest_schema: fields.remove("manifest"),
+        })
+    }
+}
+
+/// One line, no control characters, bounded length
+fn clean_value(value: &str) -> String {
+    value
+        .chars()
+        .map(|c| if c.is_control() { ' ' } else { c })
+        .take(MAX_PREAMBLE_VALUE_CHARS)
+        .collect::<String>()
+        .trim()
+        .to_string()
+}
+
+/// Armor a binary age file and put `preamble` in front of it
+pub fn armor_archive(binary: &[u8], preamble: &ArchivePreamble) -> io::Result<Vec<u8>> {
+    let mut output = preamble.render().into_bytes();
+    let mut writer = ArmoredWriter::wrap_output(&mut output, Format::AsciiArmor)?;
+    writer.write_all(binary)?;
+    writer.finish()?;
+    Ok(output)
+}
+
+/// Consume the preamble's comment lines, if any, and parse them
+///
+/// Leaves `reader` at the armor (or binary header) that follows. Returns
+/// `None` when there is no preamble or it names no app.
+pub fn skip_preamble<R: BufRead>(reader: &mut R) -> io::Result<Option<ArchivePreamble>> {
+    let mut lines = Vec::new();
+    let mut consumed = 0;
+    while reader.fill_buf()?.first() == Some(&b'#') {
+        let mut line = Vec::new();
+        let limit = (MAX_PREAMBLE_LEN - consumed) as u64;
+        reader.by_ref().take(limit).read_until(b'\n', &mut line)?;
+        if !line.ends_with(b"\n") {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "Archive preamble is too long or not followed by the archive",
+            ));
+        }
+        consumed += line.len();
+        lines.push(String::from_utf8_lossy(&line).trim_end().to_string());
+    }
+    Ok(ArchivePreamble::parse(&lines))
+}
+
+/// Reader over the binary age file in `reader`, armored or not
+///
+/// Skips any preamble; armored input is decoded, binary input passes through.
+pub fn archive_reader<R: BufRead>(mut reader: R) -> io::Result<ArmoredReader<R>> {
+    skip_preamble(&mut reader)?;
+    Ok(ArmoredReader::new(reader))
+}
+
+/// Read the archive at `path` as a binary age file, armored or not
+pub fn read_age_archive(path: &Path) -> io::Result<Vec<u8>> {
+    let file = std::fs::File::open(path)?;
+    let mut binary = Vec::new();
+    archive_reader(BufReader::new(file))?.read_to_end(&mut binary)?;
+    Ok(binary)
+}
+
+/// The preamble of the archive at `path`, reading only its first lines
+pub fn read_archive_preamble(path: &Path) -> io::Result<Option<ArchivePreamble>> {
+    let file = std::fs::File::open(path)?;
+    skip_preamble(&mut BufReader::new(file))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use age::x25519::Identity;
+    use std::iter;
+
+    fn encrypt(identity: &Identity, plaintext: &[u8]) -> Vec<u8> {
+        let recipient = identity.to_public();
+        let encryptor =
+            age::Encryptor::with_recipients(iter::once(&recipient as &dyn age::Recipient)).unwrap();
+        let mut encrypted = Vec::new();
+        let mut writer = encryptor.wrap_output(&mut encrypted).unwrap();
+        writer.write_all(plaintext).unwrap();
+        writer.finish().unwrap();
+        encrypted
+    }
+
+    fn decrypt(identity: &Identity, reader: impl Read) -> Vec<u8> {
+        let decryptor = age::Decryptor::new(reader).unwrap();
+        let mut plaintext = Vec::new();
+        decryptor
+            .decrypt(iter::once(identity as &dyn age::Identity))
+            .unwrap()
+            .read_to_end(&mut plaintext)
+            .unwrap();
+        plaintext
+    }
+
+    fn preamble(vault_name: Option<&str>) -> ArchivePreamble {
+        let created = DateTime::parse_from_rfc3339("2025-01-13T10:00:00Z").unwrap();
+        ArchivePreamble::new(created.into(), "barqly.vault.manifest/2", vault_name)
+    }
+
+    #[test]
+    fn test_preamble_round_trips_and_omits_vault_name_by_default() {
+        let private = preamble(None);
+        let rendered = private.render();
+        assert!(!rendered.contains("# vault:"));
+        assert!(rendered.contains("# recovery: https://barqly.com/recovery\n"));
+
+        let lines: Vec<String> = rendered.lines().map(str::to_string).collect();
+        assert_eq!(ArchivePreamble::parse(&lines), Some(private));
+
+        let named = preamble(Some("Family Docs: 2024"));
+        let lines: Vec<String> = named.render().lines().map(str::to_string).collect();
+        assert_eq!(ArchivePreamble::parse(&lines), Some(named));
+    }
+
+    #[test]
+    fn test_preamble_values_stay_on_one_line() {
+        let sneaky = preamble(Some("Family\n-----BEGIN AGE ENCRYPTED FILE-----"));
+        let rendered = sneaky.render();
+        assert!(rendered.lines().all(|line| line.starts_with('#')));
+    }
+
+    #[test]
+    fn test_armored_and_binary_archives_read_the_same() {
+        let identity = Identity::generate();
+        let binary = encrypt(&identity, b"family documents");
+        let armored = armor_archive(&binary, &preamble(None)).unwrap();
+
+        let mut reader = &armored[..];
+        assert_eq!(
+            skip_preamble(&mut reader).unwrap().unwrap().app,
+            PREAMBLE_APP_NAME
+        );
+        assert!(reader.starts_with(ARMOR_BEGIN_LINE.as_bytes()));
+
+        let mut from_armored = Vec::new();
+        archive_reader(&armored[..])
+            .unwrap()
+            .read_to_end(&mut from_armored)
+            .unwrap();
+        assert_eq!(from_armored, binary);
+
+        let mut from_binary = Vec::new();
+        archive_reader(&binary[..])
+            .unwrap()
+            .read_to_end(&mut from_binary)
+            .unwrap();
+        assert_eq!(from_binary, binary);
+
+        assert_eq!(
+            decrypt(&identity, archive_reader(&armored[..]).unwrap()),
+            b"family documents"
+        );
+    }
+
+    #[test]
+    fn test_binary_archive_has_no_preamble() {
+        let binary = encrypt(&Identity::generate(), b"data");
+        assert_eq!(skip_preamble(&mut &binary[..]).unwrap(), None);
+    }
+
+    #[test]
+    fn test_unterminated_preamble_is_rejected() {
+        let endless = format!("# app: {}", "x".repeat(MAX_PREAMBLE_LEN));
+        assert!(skip_preamble(&mut endless.as_bytes()).is_err());
+    }
+}
@@ -0,0 +1,320 @@
+//! age header inspection
+//!
+//! Reads the recipient stanzas of an age file without touching its payload,
+//! so a decrypt can tell up front whether the selected key was a recipient.
+//!
+//! What a stanza reveals depends on its type:
+//! - `X25519` stanzas are anonymous; only their count is known.
+//! - `piv-p256` stanzas (age-plugin-yubikey) carry a 4-byte tag derived from
+//!   the recipient's public key, so a registered YubiKey can be matched.
+//! - `scrypt` marks a file encrypted directly to a passphrase.
+
+use super::age_armor::archive_reader;
+use crate::services::crypto::domain::{CryptoError, CryptoResult as Result};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD_NO_PAD;
+use sha2::{Digest, Sha256};
+use std::io::Read;
+use std::path::Path;
+
+/// First line of every binary age file
+pub const AGE_HEADER_VERSION_LINE: &str = "age-encryption.org/v1";
+
+/// Headers longer than this are treated as malformed
+pub const MAX_AGE_HEADER_LEN: u64 = 64 * 1024;
+
+const STANZA_PREFIX: &str = "-> ";
+const MAC_PREFIX: &str = "---";
+const YUBIKEY_RECIPIENT_HRP: &str = "age1yubikey";
+const BECH32_CHARSET: &[u8] = b"qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+
+/// A recipient stanza's type and arguments (its body is not kept)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HeaderStanza {
+    pub kind: String,
+    pub args: Vec<String>,
+}
+
+/// Recipient stanzas of an age header
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AgeHeader {
+    pub stanzas: Vec<HeaderStanza>,
+}
+
+impl AgeHeader {
+    /// Number of anonymous X25519 recipients
+    pub fn x25519_count(&self) -> usize {
+        self.stanzas.iter().filter(|s| s.kind == "X25519").count()
+    }
+
+    /// Tags of the YubiKey recipients
+    pub fn piv_p256_tags(&self) -> Vec<&str> {
+        self.stanzas
+            .iter()
+            .filter(|s| s.kind == "piv-p256")
+            .filter_map(|s| s.args.first().map(String::as_str))
+            .collect()
+    }
+
+    /// The file is encrypted directly to a passphrase
+    pub fn is_passphrase_encrypted(&self) -> bool {
+        self.stanzas.iter().any(|s| s.kind == "scrypt")
+    }
+}
+
+/// Read the header of the age file at `path`, armored or not
+pub fn read_age_header_file(path: &Path) -> Result<AgeHeader> {
+    let file = std::fs::File::open(path).map_err(|e| {
+        CryptoError::InvalidInput(format!("Failed to open '{}': {}", path.display(), e))
+    })?;
+    let reader =
+        archive_reader(std::io::BufReader::new(file)).map_err(|e| malformed(&e.to_string()))?;
+    read_age_header(reader)
+}
+
+/// Parse an age header from `reader`
+///
+/// Reads byte by byte and stops right after the MAC line, so nothing past
+/// the header (and never more than `MAX_AGE_HEADER_LEN`) is consumed.
+/// Armored files are rejected as unsupported; `read_age_header_file`
+/// decodes them first.
+pub fn read_age_header(reader: impl Read) -> Result<AgeHeader> {
+    let mut bytes = reader.take(MAX_AGE_HEADER_LEN).bytes();
+    let mut next_line = || -> Result<Option<String>> {
+        let mut line = Vec::new();
+        for byte in bytes.by_ref() {
+            let byte = byte.map_err(|e| CryptoError::IoError(e.to_string()))?;
+            if byte == b'\n' {
+                return String::from_utf8(line)
+                    .map(Some)
+                    .map_err(|_| malformed("header line is not UTF-8"));
+            }
+            line.push(byte);
+        }
+        Ok(None)
+    };
+
+    match next_line()? {
+        Some(line) if line == AGE_HEADER_VERSION_LINE => {}
+        Some(line) if line.starts_with("-----BEGIN AGE ENCRYPTED FILE") => {
+            return Err(CryptoError::UnsupportedFormat(
+                "ASCII-armored age file".to_string(),
+            ));
+        }
+        _ => return Err(malformed("not an age file")),
+    }
+
+    let mut header = AgeHeader::default();
+    loop {
+        let line = next_line()?.ok_or_else(|| malformed("header ends before its MAC"))?;
+        if let Some(stanza) = line.strip_prefix(STANZA_PREFIX) {
+            let mut parts = stanza.split(' ');
+            let kind = parts
+                .next()
+                .filter(|kind| !kind.is_empty())
+                .ok_or_else(|| malformed("stanza without a type"))?;
+            header.stanzas.push(HeaderStanza {
+                kind: kind.to_string(),
+                args: parts.map(str::to_string).collect(),
+            });
+        } else if line.starts_with(MAC_PREFIX) {
+            return Ok(header);
+        } else if header.stanzas.is_empty() {
+            return Err(malformed("stanza body before any stanza"));
+        }
+    }
+}
+
+/// The `piv-p256` stanza tag for an `age1yubikey1…` recipient
+///
+/// The tag is the first four bytes of SHA-256 over the compressed P-256
+/// public key, base64-encoded without padding. Returns `None` for anything
+/// that isn't a valid YubiKey recipient.
+pub fn yubikey_recipient_tag(recipient: &str) -> Option<String> {
+    let public_key = decode_bech32(recipient, YUBIKEY_RECIPIENT_HRP)?;
+    if public_key.len() != 33 {
+        return None;
+    }
+    let digest = Sha256::digest(&public_key);
+    Some(STANDARD_NO_PAD.encode(&digest[..4]))
+}
+
+/// The recipient is a native X25519 `age1…` key
+pub fn is_x25519_recipient(recipient: &str) -> bool {
+    let lower = recipient.to_ascii_lowercase();
+    lower.starts_with("age1") && !lower.starts_with(YUBIKEY_RECIPIENT_HRP)
+}
+
+fn malformed(reason: &str) -> CryptoError {
+    CryptoError::UnsupportedFormat(format!("Malformed age header: {}", reason))
+}
+
+/// Decode a BIP-173 bech32 string with the expected human-readable part
+fn decode_bech32(encoded: &str, expected_hrp: &str) -> Option<Vec<u8>> {
+    let lower = encoded.to_ascii_lowercase();
+    let separator = lower.rfind('1')?;
+    let (hrp, data) = (&lower[..separator], &lower[separator + 1..]);
+    if hrp != expected_hrp || data.len() < 6 {
+        return None;
+    }
+
+    let values: Vec<u8> = data
+        .bytes()
+        .map(|c| BECH32_CHARSET.iter().position(|&v| v == c).map(|p| p as u8))
+        .collect::<Option<_>>()?;
+
+    let mut checked: Vec<u8> = hrp.bytes().map(|b| b >> 5).collect();
+    checked.push(0);
+    checked.extend(hrp.bytes().map(|b| b & 31));
+    checked.extend(&values);
+    if bech32_polymod(&checked) != 1 {
+        return None;
+    }
+
+    // Regroup 5-bit values into bytes, rejecting non-zero padding
+    let mut bytes = Vec::new();
+    let (mut acc, mut bits) = (0u32, 0u32);
+    for &value in &values[..values.len() - 6] {
+        acc = (acc << 5) | value as u32;
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            bytes.push((acc >> bits) as u8);
+        }
+    }
+    if bits >= 5 || (acc & ((1 << bits) - 1)) != 0 {
+        return None;
+    }
+    Some(bytes)
+}
+
+fn bech32_polymod(values: &[u8]) -> u32 {
+    const GENERATOR: [u32; 5] = [0x3b6a57b2, 0x26508e6d, 0x1ea119fa, 0x3d4233dd, 0x2a1462b3];
+    let mut checksum = 1u32;
+    for &value in values {
+        let top = checksum >> 25;
+        checksum = ((checksum & 0x1ffffff) << 5) ^ value as u32;
+        for (i, generator) in GENERATOR.iter().enumerate() {
+            if (top >> i) & 1 == 1 {
+                checksum ^= generator;
+            }
+        }
+    }
+    checksum
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use age::x25519::Identity;
+    use std::io::{Cursor, Write};
+
+    fn encrypt_to(recipients: Vec<Box<dyn age::Recipient + Send>>) -> Vec<u8> {
+        let encryptor = age::Encryptor::with_recipients(
+            recipients.iter().map(|r| r.as_ref() as &dyn age::Recipient),
+        )
+        .unwrap();
+        let mut encrypted = Vec::new();
+        let mut writer = encryptor.wrap_output(&mut encrypted).unwrap();
+        writer.write_all(b"payload").unwrap();
+        writer.finish().unwrap();
+        encrypted
+    }
+
+    #[test]
+    fn test_reads_x25519_stanzas_and_stops_at_mac() {
+        let recipients: Vec<Box<dyn age::Recipient + Send>> = vec![
+            Box::new(Identity::generate().to_public()),
+            Box::new(Identity::generate().to_public()),
+        ];
+        let encrypted = encrypt_to(recipients);
+
+        let mut cursor = Cursor::new(&encrypted);
+        let header = read_age_header(&mut cursor).unwrap();
+        assert_eq!(header.x25519_count(), 2);
+        assert!(header.piv_p256_tags().is_empty());
+
+        // The payload starts right after the MAC line
+        let consumed = cursor.position() as usize;
+        let text = String::from_utf8_lossy(&encrypted[..consumed]);
+        assert!(text.lines().last().unwrap().starts_with("--- "));
+        assert!(consumed < encrypted.len());
+    }
+
+    #[test]
+    fn test_reads_plugin_stanzas() {
+        let header = b"age-encryption.org/v1\n\
+            -> piv-p256 AbCdEf AzLZq1mXvLpo5HpvMC8J6CFS9MAjsmvLfZAQ0hbChXwI\n\
+            bm90IGEgcmVhbCBib2R5\n\
+            -> X25519 c2hhcmU\n\
+            Ym9keQ\n\
+            --- bWFj\npayload";
+        let parsed = read_age_header(&header[..]).unwrap();
+        assert_eq!(parsed.piv_p256_tags(), ["AbCdEf"]);
+        assert_eq!(parsed.x25519_count(), 1);
+    }
+
+    #[test]
+    fn test_rejects_non_age_and_truncated_input() {
+        assert!(read_age_header(&b"PK\x03\x04 zip file"[..]).is_err());
+        assert!(read_age_header(&b"age-encryption.org/v1\n-> X25519 abc\n"[..]).is_err());
+        assert!(matches!(
+            read_age_header(&b"-----BEGIN AGE ENCRYPTED FILE-----\n"[..]),
+            Err(CryptoError::UnsupportedFormat(_))
+        ));
+
+        let endless = std::iter::repeat_n(b'a', MAX_AGE_HEADER_LEN as usize * 2);
+        let mut oversized = b"age-encryption.org/v1\n-> X25519 ".to_vec();
+        oversized.extend(endless);
+        assert!(read_age_header(&oversized[..]).is_err());
+    }
+
+    #[test]
+    fn test_yubikey_recipient_tag() {
+        // 33-byte compressed point 0x02 || 32 x 0x11, encoded as bech32
+        let mut point = vec![0x02];
+        point.extend([0x11; 32]);
+        let recipient = encode_bech32(YUBIKEY_RECIPIENT_HRP, &point);
+        let expected = STANDARD_NO_PAD.encode(&Sha256::digest(&point)[..4]);
+
+        assert_eq!(yubikey_recipient_tag(&recipient), Some(expected));
+        let mut corrupted = recipient.clone();
+        let last = corrupted.pop().unwrap();
+        corrupted.push(if last == 'q' { 'p' } else { 'q' });
+        assert_eq!(yubikey_recipient_tag(&corrupted), None);
+        assert!(!is_x25519_recipient(&recipient));
+        assert!(is_x25519_recipient(
+            &Identity::generate().to_public().to_string()
+        ));
+    }
+
+    fn encode_bech32(hrp: &str, data: &[u8]) -> String {
+        let mut values = Vec::new();
+        let (mut acc, mut bits) = (0u32, 0u32);
+        for &byte in data {
+            acc = (acc << 8) | byte as u32;
+            bits += 8;
+            while bits >= 5 {
+                bits -= 5;
+                values.push(((acc >> bits) & 31) as u8);
+            }
+        }
+        if bits > 0 {
+            values.push(((acc << (5 - bits)) & 31) as u8);
+        }
+
+        let mut checked: Vec<u8> = hrp.bytes().map(|b| b >> 5).collect();
+        checked.push(0);
+        checked.extend(hrp.bytes().map(|b| b & 31));
+        checked.extend(&values);
+        checked.extend([0; 6]);
+        let polymod = bech32_polymod(&checked) ^ 1;
+        values.extend((0..6).map(|i| ((polymod >> (5 * (5 - i))) & 31) as u8));
+
+        let encoded: String = values
+            .iter()
+            .map(|&v| BECH32_CHARSET[v as usize] as char)
+            .collect();
+        format!("{hrp}1{encoded}")
+    }
+}
@@ -7,7 +7,9 @@
 use std::str::FromStr;
 
 use super::{CryptoError, Result};
+use crate::logging::in_current_trace;
 use crate::prelude::*;
+use crate::services::key_management::yubikey::infrastructure::pty::age_ops::YubiKeyDecryptSession;
 use crate::services::key_management::yubikey::infrastructure::pty::core::get_age_path;
 
 // Windows-specific process creation flags to hide console windows
@@ -360,7 +362,7 @@ pub fn encrypt_data_multi_recipient_cli(data: &[u8], recipients: &[PublicKey]) -
     let data_vec = data.to_vec();
 
     // Spawn thread to write stdin concurrently
-    let stdin_thread = std::thread::spawn(move || -> Result<()> {
+    let stdin_thread = std::thread::spawn(in_current_trace(move || -> Result<()> {
         let mut stdin = stdin;
         stdin.write_all(&data_vec).map_err(|e| {
             error!(
@@ -375,7 +377,7 @@ pub fn encrypt_data_multi_recipient_cli(data: &[u8], recipients: &[PublicKey]) -
         drop(stdin);
         trace!("Stdin write completed and closed");
         Ok(())
-    });
+    }));
 
     // Wait for process and collect output while stdin is being written concurrently
     // wait_with_output() internally reads stdout/stderr preventing deadlock
@@ -457,21 +459,7 @@ pub fn decrypt_data_yubikey_cli(
     debug_assert!(!pin.is_empty(), "PIN cannot be empty");
 
     // Extract YubiKey details from key entry
-    let (serial, slot, recipient, identity_tag) = match key_entry {
-        crate::services::key_management::shared::KeyEntry::Yubikey {
-            serial,
-            slot,
-            recipient,
-            identity_tag,
-            ..
-        } => (serial, slot, recipient, identity_tag),
-        _ => {
-            error!("Invalid key entry type for YubiKey decryption");
-            return Err(CryptoError::DecryptionFailed(
-                "Expected YubiKey key entry".to_string(),
-            ));
-        }
-    };
+    let (serial, slot, recipient, identity_tag) = yubikey_identity(key_entry)?;
 
     trace!(
         encrypted_size = encrypted_data.len(),
@@ -498,6 +486,42 @@ pub fn decrypt_data_yubikey_cli(
     })
 }
 
+/// Open a YubiKey session for decrypting several archives with one unlock
+///
+/// The PIN is collected once and reused for every decryption in the session.
+pub fn open_yubikey_decrypt_session(
+    key_entry: &crate::services::key_management::shared::KeyEntry,
+    pin: &str,
+) -> Result<YubiKeyDecryptSession> {
+    let (serial, slot, recipient, identity_tag) = yubikey_identity(key_entry)?;
+
+    YubiKeyDecryptSession::open(serial, *slot, recipient, identity_tag, pin).map_err(|e| {
+        error!(error = %e, "Failed to open YubiKey decryption session");
+        CryptoError::DecryptionFailed(format!("YubiKey session failed: {e}"))
+    })
+}
+
+/// Serial, slot, recipient and identity tag of a YubiKey key entry
+fn yubikey_identity(
+    key_entry: &crate::services::key_management::shared::KeyEntry,
+) -> Result<(&String, &u8, &String, &String)> {
+    match key_entry {
+        crate::services::key_management::shared::KeyEntry::Yubikey {
+            serial,
+            slot,
+            recipient,
+            identity_tag,
+            ..
+        } => Ok((serial, slot, recipient, identity_tag)),
+        _ => {
+            error!("Invalid key entry type for YubiKey decryption");
+            Err(CryptoError::DecryptionFailed(
+                "Expected YubiKey key entry".to_string(),
+            ))
+        }
+    }
+}
+
 /// Decrypt data using CLI approach for YubiKey support (legacy function)
 ///
 /// This function uses the age CLI to support plugin-based decryption (YubiKey)
@@ -593,7 +617,7 @@ pub fn decrypt_data_cli(encrypted_data: &[u8]) -> Result<Vec<u8>> {
     let data_vec = encrypted_data.to_vec();
 
     // Spawn thread to write stdin concurrently
-    let stdin_thread = std::thread::spawn(move || -> Result<()> {
+    let stdin_thread = std::thread::spawn(in_current_trace(move || -> Result<()> {
         let mut stdin = stdin;
         stdin.write_all(&data_vec).map_err(|e| {
             error!(
@@ -608,7 +632,7 @@ pub fn decrypt_data_cli(encrypted_data: &[u8]) -> Result<Vec<u8>> {
         drop(stdin);
         trace!("Stdin write completed and closed");
         Ok(())
-    });
+    }));
 
     // Wait for process and collect output while stdin is being written concurrently
     // wait_with_output() internally reads stdout/stderr preventing deadlock
@@ -0,0 +1,330 @@
+//! Chunk-by-chunk salvage of damaged age payloads
+//!
+//! An age payload is a STREAM of 64 KiB chunks, each authenticated on its
+//! own (ChaCha20-Poly1305 keyed from the file key and the payload nonce). A
+//! bad sector only breaks the chunks it touches, so instead of stopping at
+//! the first authentication failure, salvage decrypts every chunk it can and
+//! records the plaintext ranges it couldn't.
+//!
+//! Only plaintext that authenticated is returned; lost ranges are zero-filled
+//! so later offsets stay put. The header has no redundancy: if it is damaged
+//! (or its MAC doesn't verify) nothing can be salvaged.
+
+use crate::services::crypto::domain::{CryptoError, CryptoResult};
+use age::secrecy::ExposeSecret;
+use age_core::format::Stanza;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD_NO_PAD;
+use chacha20poly1305::aead::{Aead, KeyInit};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use hkdf::Hkdf;
+use hmac::{Hmac, Mac};
+use serde::Serialize;
+use sha2::Sha256;
+
+use super::age_header::AGE_HEADER_VERSION_LINE;
+
+/// Plaintext bytes per STREAM chunk
+pub const STREAM_CHUNK_SIZE: usize = 64 * 1024;
+
+const TAG_SIZE: usize = 16;
+const ENCRYPTED_CHUNK_SIZE: usize = STREAM_CHUNK_SIZE + TAG_SIZE;
+const PAYLOAD_NONCE_SIZE: usize = 16;
+const STANZA_BODY_LINE_LEN: usize = 64;
+const MAX_SALVAGE_HEADER_LEN: usize = 64 * 1024;
+
+/// Half-open byte range `[start, end)`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, specta::Type)]
+pub struct ByteRange {
+    pub start: u64,
+    pub end: u64,
+}
+
+impl ByteRange {
+    pub fn len(&self) -> u64 {
+        self.end - self.start
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.end <= self.start
+    }
+
+    /// Bytes shared with `other`
+    pub fn overlap(&self, other: &ByteRange) -> u64 {
+        self.end
+            .min(other.end)
+            .saturating_sub(self.start.max(other.start))
+    }
+}
+
+/// What could be decrypted from a possibly damaged payload
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SalvagedPayload {
+    /// Decrypted payload with `lost_ranges` zero-filled
+    pub plaintext: Vec<u8>,
+    /// Plaintext ranges whose chunks failed authentication, merged and sorted
+    pub lost_ranges: Vec<ByteRange>,
+    pub chunk_count: u64,
+    pub lost_chunk_count: u64,
+}
+
+impl SalvagedPayload {
+    /// Every chunk authenticated
+    pub fn is_intact(&self) -> bool {
+        self.lost_ranges.is_empty()
+    }
+
+    /// Length of the undamaged plaintext before the first lost range
+    pub fn intact_prefix_len(&self) -> usize {
+        self.lost_ranges
+            .first()
+            .map_or(self.plaintext.len(), |range| range.start as usize)
+    }
+}
+
+struct SalvageHeader {
+    stanzas: Vec<Stanza>,
+    /// Header bytes covered by the MAC (through the `---` marker)
+    mac_input_len: usize,
+    mac: Vec<u8>,
+    payload_start: usize,
+}
+
+/// Decrypt every authenticating chunk of the binary age file `data`
+///
+/// Fails only when the header can't be read, `identity` isn't a recipient,
+/// or the header MAC doesn't verify.
+pub fn salvage_payload(data: &[u8], identity: &dyn age::Identity) -> CryptoResult<SalvagedPayload> {
+    let header = parse_header(data)?;
+    let file_key = identity
+        .unwrap_stanzas(&header.stanzas)
+        .ok_or_else(|| {
+            CryptoError::DecryptionFailed("The key is not a recipient of this archive".to_string())
+        })?
+        .map_err(|e| CryptoError::DecryptionFailed(e.to_string()))?;
+    let file_key = file_key.expose_secret();
+
+    let mut mac = <Hmac<Sha256> as Mac>::new_from_slice(&hkdf(&[], b"header", file_key))
+        .map_err(|e| CryptoError::DecryptionFailed(e.to_string()))?;
+    mac.update(&data[..header.mac_input_len]);
+    mac.verify_slice(&header.mac).map_err(|_| {
+        CryptoError::DecryptionFailed(
+            "The archive header is damaged; nothing can be salvaged".to_string(),
+        )
+    })?;
+
+    let payload = &data[header.payload_start..];
+    if payload.len() < PAYLOAD_NONCE_SIZE {
+        return Err(CryptoError::DecryptionFailed(
+            "The archive ends before its payload".to_string(),
+        ));
+    }
+    let (nonce, chunks) = payload.split_at(PAYLOAD_NONCE_SIZE);
+    let cipher = ChaCha20Poly1305::new(Key::from_slice(&hkdf(nonce, b"payload", file_key)));
+
+    let chunk_count = chunks.len().div_ceil(ENCRYPTED_CHUNK_SIZE).max(1);
+    let mut plaintext = Vec::with_capacity(chunk_count * STREAM_CHUNK_SIZE);
+    let mut lost_ranges: Vec<ByteRange> = Vec::new();
+    let mut lost_chunk_count = 0;
+    for index in 0..chunk_count {
+        let start = (index * ENCRYPTED_CHUNK_SIZE).min(chunks.len());
+        let chunk = &chunks[start..(start + ENCRYPTED_CHUNK_SIZE).min(chunks.len())];
+        let last = index + 1 == chunk_count;
+        match cipher.decrypt(&chunk_nonce(index as u64, last), chunk) {
+            Ok(decrypted) => plaintext.extend_from_slice(&decrypted),
+            Err(_) => {
+                lost_chunk_count += 1;
+                let offset = plaintext.len() as u64;
+                let len = chunk.len().saturating_sub(TAG_SIZE);
+                plaintext.resize(plaintext.len() + len, 0);
+                let range = ByteRange {
+                    start: offset,
+                    end: offset + len as u64,
+                };
+                match lost_ranges.last_mut() {
+                    Some(previous) if previous.end == range.start => previous.end = range.end,
+                    _ => lost_ranges.push(range),
+                }
+            }
+        }
+    }
+
+    Ok(SalvagedPayload {
+        plaintext,
+        lost_ranges,
+        chunk_count: chunk_count as u64,
+        lost_chunk_count,
+    })
+}
+
+/// STREAM nonce: 11-byte big-endian chunk counter, then the last-chunk flag
+fn chunk_nonce(counter: u64, last: bool) -> Nonce {
+    let mut nonce = [0u8; 12];
+    nonce[3..11].copy_from_slice(&counter.to_be_bytes());
+    nonce[11] = u8::from(last);
+    Nonce::clone_from_slice(&nonce)
+}
+
+fn hkdf(salt: &[u8], info: &[u8], ikm: &[u8]) -> [u8; 32] {
+    let mut okm = [0u8; 32];
+    Hkdf::<Sha256>::new(Some(salt), ikm)
+        .expand(info, &mut okm)
+        .expect("32 bytes is a valid HKDF-SHA256 output length");
+    okm
+}
+
+fn parse_header(data: &[u8]) -> CryptoResult<SalvageHeader> {
+    let mut position = 0;
+    if next_line(data, &mut position)?.1 != AGE_HEADER_VERSION_LINE {
+        return Err(damaged("not a binary age file"));
+    }
+
+    let mut stanzas: Vec<Stanza> = Vec::new();
+    let mut body: Option<String> = None;
+    loop {
+        let (line_start, line) = next_line(data, &mut position)?;
+        if let Some(encoded) = body.as_mut() {
+            encoded.push_str(line);
+            if line.len() < STANZA_BODY_LINE_LEN {
+                let decoded = STANDARD_NO_PAD
+                    .decode(body.take().unwrap_or_default())
+                    .map_err(|_| damaged("a stanza body is not base64"))?;
+                if let Some(stanza) = stanzas.last_mut() {
+                    stanza.body = decoded;
+                }
+            }
+        } else if let Some(stanza) = line.strip_prefix("-> ") {
+            let mut parts = stanza.split(' ').map(str::to_string);
+            let tag = parts.next().unwrap_or_default();
+            stanzas.push(Stanza {
+                tag,
+                args: parts.collect(),
+                body: Vec::new(),
+            });
+            body = Some(String::new());
+        } else if let Some(mac) = line.strip_prefix("--- ") {
+            let mac = STANDARD_NO_PAD
+                .decode(mac)
+                .map_err(|_| damaged("its MAC is not base64"))?;
+            return Ok(SalvageHeader {
+                stanzas,
+                mac_input_len: line_start + 3,
+                mac,
+                payload_start: line_start + line.len() + 1,
+            });
+        } else {
+            return Err(damaged("a line is not part of any stanza"));
+        }
+    }
+}
+
+/// The header line at `position` and where it starts, advancing past it
+fn next_line<'a>(data: &'a [u8], position: &mut usize) -> CryptoResult<(usize, &'a str)> {
+    let start = *position;
+    let limit = data.len().min(MAX_SALVAGE_HEADER_LEN).max(start);
+    let end = data[start..limit]
+        .iter()
+        .position(|&b| b == b'\n')
+        .map(|i| start + i)
+        .ok_or_else(|| damaged("it ends before its MAC"))?;
+    *position = end + 1;
+    let line = std::str::from_utf8(&data[start..end]).map_err(|_| damaged("not UTF-8"))?;
+    Ok((start, line))
+}
+
+fn damaged(reason: &str) -> CryptoError {
+    CryptoError::DecryptionFailed(format!(
+        "The archive header is damaged ({}); nothing can be salvaged",
+        reason
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use age::x25519::Identity;
+    use std::io::Write;
+    use std::iter;
+
+    fn encrypt(identity: &Identity, plaintext: &[u8]) -> Vec<u8> {
+        let recipient = identity.to_public();
+        let encryptor =
+            age::Encryptor::with_recipients(iter::once(&recipient as &dyn age::Recipient)).unwrap();
+        let mut encrypted = Vec::new();
+        let mut writer = encryptor.wrap_output(&mut encrypted).unwrap();
+        writer.write_all(plaintext).unwrap();
+        writer.finish().unwrap();
+        encrypted
+    }
+
+    fn sample(len: usize) -> Vec<u8> {
+        (0..len).map(|i| (i * 31 % 251) as u8).collect()
+    }
+
+    /// Offset of chunk `index` within the age file
+    fn chunk_offset(encrypted: &[u8], index: usize) -> usize {
+        let header = parse_header(encrypted).unwrap();
+        header.payload_start + PAYLOAD_NONCE_SIZE + index * ENCRYPTED_CHUNK_SIZE
+    }
+
+    #[test]
+    fn test_intact_payload_matches_age() {
+        let identity = Identity::generate();
+        let plaintext = sample(3 * STREAM_CHUNK_SIZE + 100);
+        let encrypted = encrypt(&identity, &plaintext);
+
+        let salvaged = salvage_payload(&encrypted, &identity).unwrap();
+        assert!(salvaged.is_intact());
+        assert_eq!(salvaged.chunk_count, 4);
+        assert_eq!(salvaged.plaintext, plaintext);
+
+        let empty = salvage_payload(&encrypt(&identity, b""), &identity).unwrap();
+        assert!(empty.is_intact());
+        assert!(empty.plaintext.is_empty());
+    }
+
+    #[test]
+    fn test_damaged_chunks_are_skipped_and_reported() {
+        let identity = Identity::generate();
+        let plaintext = sample(4 * STREAM_CHUNK_SIZE + 10);
+        let mut encrypted = encrypt(&identity, &plaintext);
+        for index in [1, 2] {
+            let offset = chunk_offset(&encrypted, index) + 100;
+            encrypted[offset] ^= 0xff;
+        }
+
+        let salvaged = salvage_payload(&encrypted, &identity).unwrap();
+        assert_eq!(salvaged.lost_chunk_count, 2);
+        let chunk = STREAM_CHUNK_SIZE as u64;
+        assert_eq!(
+            salvaged.lost_ranges,
+            vec![ByteRange {
+                start: chunk,
+                end: 3 * chunk
+            }]
+        );
+        assert_eq!(salvaged.intact_prefix_len(), STREAM_CHUNK_SIZE);
+        assert_eq!(salvaged.plaintext.len(), plaintext.len());
+        assert_eq!(
+            &salvaged.plaintext[..STREAM_CHUNK_SIZE],
+            &plaintext[..STREAM_CHUNK_SIZE]
+        );
+        assert_eq!(
+            &salvaged.plaintext[3 * STREAM_CHUNK_SIZE..],
+            &plaintext[3 * STREAM_CHUNK_SIZE..]
+        );
+    }
+
+    #[test]
+    fn test_damaged_header_and_wrong_key_fail() {
+        let identity = Identity::generate();
+        let encrypted = encrypt(&identity, &sample(1000));
+
+        assert!(salvage_payload(&encrypted, &Identity::generate()).is_err());
+
+        let header = parse_header(&encrypted).unwrap();
+        let mut damaged = encrypted.clone();
+        damaged[header.mac_input_len - 10] ^= 0x01;
+        assert!(salvage_payload(&damaged, &identity).is_err());
+    }
+}
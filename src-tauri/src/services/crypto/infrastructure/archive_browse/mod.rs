@@ -0,0 +1,29 @@
+//! Read-only localhost browsing of decrypted archive snapshots
+//!
+//! `SnapshotStore` holds the decrypted files; `BrowseServer` serves them over
+//! HTTP on 127.0.0.1 behind an unguessable token path.
+
+pub mod server;
+pub mod snapshot_store;
+
+pub use server::BrowseServer;
+pub use snapshot_store::{
+    DEFAULT_SNAPSHOT_MEMORY_BYTES, DEFAULT_SNAPSHOT_SPILL_BYTES, SnapshotLimits, SnapshotStore,
+};
+
+/// Build a tar.gz archive from in-memory files
+#[cfg(test)]
+pub(crate) fn test_archive(files: &[(&str, &[u8])]) -> Vec<u8> {
+    use flate2::Compression;
+    use flate2::write::GzEncoder;
+
+    let mut builder = tar::Builder::new(GzEncoder::new(Vec::new(), Compression::fast()));
+    for (path, contents) in files {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(contents.len() as u64);
+        header.set_mode(0o644);
+        header.set_cksum();
+        builder.append_data(&mut header, path, *contents).unwrap();
+    }
+    builder.into_inner().unwrap().finish().unwrap()
+}
@@ -0,0 +1,675 @@
+//! Loopback HTTP server for a decrypted snapshot
+//!
+//! Serves GET and HEAD only, one request per connection. Every path lives
+//! under a random token segment and anything without it gets a plain 404, so
+//! the open port reveals nothing. Connections from non-loopback peers and
+//! requests naming a foreign Host (DNS rebinding) are refused.
+
+use super::snapshot_store::SnapshotStore;
+use crate::prelude::*;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{Ipv4Addr, SocketAddr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+const ACCEPT_POLL_INTERVAL: Duration = Duration::from_millis(50);
+const REQUEST_READ_TIMEOUT: Duration = Duration::from_secs(10);
+const MAX_REQUEST_HEAD_BYTES: u64 = 8 * 1024;
+const BODY_CHUNK_BYTES: u64 = 64 * 1024;
+
+/// State shared by the accept loop and connection threads
+struct ServerContext {
+    store: Arc<SnapshotStore>,
+    token: String,
+    shutdown: AtomicBool,
+    last_activity: Mutex<Instant>,
+}
+
+impl ServerContext {
+    fn touch(&self) {
+        *self.last_activity.lock().unwrap_or_else(|e| e.into_inner()) = Instant::now();
+    }
+}
+
+/// Read-only HTTP server bound to 127.0.0.1 on a random port
+pub struct BrowseServer {
+    addr: SocketAddr,
+    context: Arc<ServerContext>,
+    accept_thread: Option<JoinHandle<()>>,
+}
+
+impl std::fmt::Debug for BrowseServer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // Never print the token
+        f.debug_struct("BrowseServer")
+            .field("addr", &self.addr)
+            .field("running", &self.accept_thread.is_some())
+            .finish()
+    }
+}
+
+impl BrowseServer {
+    /// Bind a loopback port and start serving `store` under `/{token}/`
+    pub fn start(store: Arc<SnapshotStore>, token: String) -> io::Result<Self> {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0))?;
+        listener.set_nonblocking(true)?;
+        let addr = listener.local_addr()?;
+
+        let context = Arc::new(ServerContext {
+            store,
+            token,
+            shutdown: AtomicBool::new(false),
+            last_activity: Mutex::new(Instant::now()),
+        });
+
+        let accept_thread = std::thread::Builder::new()
+            .name("archive-browse".into())
+            .spawn({
+                let context = context.clone();
+                move || accept_loop(listener, context)
+            })?;
+
+        debug!(%addr, "Archive browse server started");
+        Ok(Self {
+            addr,
+            context,
+            accept_thread: Some(accept_thread),
+        })
+    }
+
+    pub fn local_addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// URL of the snapshot index, including the access token
+    pub fn url(&self) -> String {
+        format!("http://{}/{}/", self.addr, self.context.token)
+    }
+
+    /// Time since the last request was served
+    pub fn idle_for(&self, now: Instant) -> Duration {
+        let last = *self
+            .context
+            .last_activity
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        now.saturating_duration_since(last)
+    }
+
+    /// Stop accepting connections and close the listener
+    ///
+    /// Responses already streaming stop at their next chunk.
+    pub fn stop(&mut self) {
+        self.context.shutdown.store(true, Ordering::Release);
+        if let Some(handle) = self.accept_thread.take() {
+            if handle.join().is_err() {
+                warn!("Archive browse accept thread panicked");
+            }
+            debug!(addr = %self.addr, "Archive browse server stopped");
+        }
+    }
+}
+
+impl Drop for BrowseServer {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+fn accept_loop(listener: TcpListener, context: Arc<ServerContext>) {
+    while !context.shutdown.load(Ordering::Acquire) {
+        match listener.accept() {
+            Ok((stream, peer)) => {
+                let context = context.clone();
+                let spawned = std::thread::Builder::new()
+                    .name("archive-browse-conn".into())
+                    .spawn(move || {
+                        if let Err(e) = handle_connection(stream, peer, &context) {
+                            debug!(error = %e, "Archive browse connection failed");
+                        }
+                    });
+                if let Err(e) = spawned {
+                    warn!(error = %e, "Failed to spawn archive browse connection thread");
+                }
+            }
+            Err(e) if e.kind() == io::ErrorKind::WouldBlock => {
+                std::thread::sleep(ACCEPT_POLL_INTERVAL);
+            }
+            Err(e) => {
+                warn!(error = %e, "Archive browse accept failed");
+                std::thread::sleep(ACCEPT_POLL_INTERVAL);
+            }
+        }
+    }
+}
+
+struct Request {
+    method: String,
+    target: String,
+    host: Option<String>,
+    range: Option<String>,
+}
+
+enum Body {
+    Bytes(Vec<u8>),
+    Snapshot { path: String, start: u64, len: u64 },
+}
+
+struct Response {
+    status: u16,
+    reason: &'static str,
+    headers: Vec<(&'static str, String)>,
+    body: Body,
+}
+
+impl Response {
+    fn text(status: u16, reason: &'static str) -> Self {
+        Self {
+            status,
+            reason,
+            headers: vec![("Content-Type", "text/plain; charset=utf-8".into())],
+            body: Body::Bytes(reason.as_bytes().to_vec()),
+        }
+    }
+
+    fn content_length(&self) -> u64 {
+        match &self.body {
+            Body::Bytes(bytes) => bytes.len() as u64,
+            Body::Snapshot { len, .. } => *len,
+        }
+    }
+}
+
+fn handle_connection(
+    mut stream: TcpStream,
+    peer: SocketAddr,
+    context: &ServerContext,
+) -> io::Result<()> {
+    if !peer.ip().is_loopback() {
+        warn!(%peer, "Refused non-loopback archive browse connection");
+        return Ok(());
+    }
+
+    // Accepted sockets inherit non-blocking mode on some platforms
+    stream.set_nonblocking(false)?;
+    stream.set_read_timeout(Some(REQUEST_READ_TIMEOUT))?;
+
+    let Some(request) = read_request(&stream)? else {
+        let response = Response::text(400, "Bad Request");
+        return write_response(&mut stream, response, false, context);
+    };
+
+    context.touch();
+    let head_only = request.method == "HEAD";
+    write_response(&mut stream, route(&request, context), head_only, context)
+}
+
+/// Read the request line and the headers this server cares about
+///
+/// Returns `None` for a malformed or oversized request head.
+fn read_request(stream: &TcpStream) -> io::Result<Option<Request>> {
+    let mut reader = BufReader::new(stream.take(MAX_REQUEST_HEAD_BYTES));
+    let mut line = String::new();
+    reader.read_line(&mut line)?;
+
+    let mut parts = line.split_whitespace();
+    let (Some(method), Some(target), Some(_version)) = (parts.next(), parts.next(), parts.next())
+    else {
+        return Ok(None);
+    };
+    let mut request = Request {
+        method: method.to_string(),
+        target: target.to_string(),
+        host: None,
+        range: None,
+    };
+
+    loop {
+        line.clear();
+        if reader.read_line(&mut line)? == 0 {
+            // Connection closed or head limit reached before the blank line
+            return Ok(None);
+        }
+        let header = line.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            let value = value.trim().to_string();
+            match name.trim().to_ascii_lowercase().as_str() {
+                "host" => request.host = Some(value),
+                "range" => request.range = Some(value),
+                _ => {}
+            }
+        }
+    }
+
+    Ok(Some(request))
+}
+
+fn route(request: &Request, context: &ServerContext) -> Response {
+    if !request.host.as_deref().is_some_and(is_loopback_host) {
+        return Response::text(403, "Forbidden");
+    }
+
+    let path = request.target.split(['?', '#']).next().unwrap_or_default();
+    let Some((token, rest)) = path.strip_prefix('/').and_then(|path| path.split_once('/')) else {
+        return Response::text(404, "Not Found");
+    };
+    if !tokens_match(token, &context.token) {
+        return Response::text(404, "Not Found");
+    }
+
+    if request.method != "GET" && request.method != "HEAD" {
+        let mut response = Response::text(405, "Method Not Allowed");
+        response.headers.push(("Allow", "GET, HEAD".into()));
+        return response;
+    }
+
+    if rest.is_empty() {
+        return index_response(&context.store);
+    }
+
+    let Some(file_path) = percent_decode(rest) else {
+        return Response::text(400, "Bad Request");
+    };
+    let Some(len) = context.store.file_len(&file_path) else {
+        return Response::text(404, "Not Found");
+    };
+
+    let mut headers = vec![("Content-Type", content_type(&file_path).to_string())];
+    match parse_range(request.range.as_deref(), len) {
+        ByteRange::Full => Response {
+            status: 200,
+            reason: "OK",
+            headers,
+            body: Body::Snapshot {
+                path: file_path,
+                start: 0,
+                len,
+            },
+        },
+        ByteRange::Partial { start, end } => {
+            headers.push(("Content-Range", format!("bytes {start}-{end}/{len}")));
+            Response {
+                status: 206,
+                reason: "Partial Content",
+                headers,
+                body: Body::Snapshot {
+                    path: file_path,
+                    start,
+                    len: end - start + 1,
+                },
+            }
+        }
+        ByteRange::Unsatisfiable => {
+            let mut response = Response::text(416, "Range Not Satisfiable");
+            response
+                .headers
+                .push(("Content-Range", format!("bytes */{len}")));
+            response
+        }
+    }
+}
+
+fn index_response(store: &SnapshotStore) -> Response {
+    let mut html = String::from(
+        "<!doctype html>\n<meta charset=\"utf-8\">\n<title>Archive snapshot</title>\n<ul>\n",
+    );
+    for path in store.paths() {
+        let size = store.file_len(&path).unwrap_or(0);
+        html.push_str(&format!(
+            "<li><a href=\"{}\">{}</a> ({} bytes)</li>\n",
+            percent_encode(&path),
+            html_escape(&path),
+            size
+        ));
+    }
+    html.push_str("</ul>\n");
+
+    Response {
+        status: 200,
+        reason: "OK",
+        headers: vec![("Content-Type", "text/html; charset=utf-8".into())],
+        body: Body::Bytes(html.into_bytes()),
+    }
+}
+
+fn write_response(
+    stream: &mut TcpStream,
+    response: Response,
+    head_only: bool,
+    context: &ServerContext,
+) -> io::Result<()> {
+    let mut head = format!("HTTP/1.1 {} {}\r\n", response.status, response.reason);
+    for (name, value) in &response.headers {
+        head.push_str(&format!("{name}: {value}\r\n"));
+    }
+    head.push_str(&format!(
+        "Content-Length: {}\r\n\
+         Accept-Ranges: bytes\r\n\
+         Cache-Control: no-store\r\n\
+         X-Content-Type-Options: nosniff\r\n\
+         Content-Security-Policy: sandbox\r\n\
+         Connection: close\r\n\r\n",
+        response.content_length()
+    ));
+    stream.write_all(head.as_bytes())?;
+    if head_only {
+        return stream.flush();
+    }
+
+    match response.body {
+        Body::Bytes(bytes) => stream.write_all(&bytes)?,
+        Body::Snapshot { path, start, len } => {
+            let mut offset = start;
+            let end = start + len;
+            while offset < end {
+                if context.shutdown.load(Ordering::Acquire) {
+                    break;
+                }
+                let chunk =
+                    context
+                        .store
+                        .read(&path, offset, BODY_CHUNK_BYTES.min(end - offset))?;
+                if chunk.is_empty() {
+                    break;
+                }
+                stream.write_all(&chunk)?;
+                offset += chunk.len() as u64;
+                context.touch();
+            }
+        }
+    }
+    stream.flush()
+}
+
+/// Host header names this server answers to
+fn is_loopback_host(host: &str) -> bool {
+    let name = match host.rsplit_once(':') {
+        Some((name, port)) if port.bytes().all(|b| b.is_ascii_digit()) => name,
+        _ => host,
+    };
+    matches!(
+        name.to_ascii_lowercase().as_str(),
+        "127.0.0.1" | "localhost" | "[::1]"
+    )
+}
+
+/// Compare tokens without an early exit on the first differing byte
+fn tokens_match(candidate: &str, token: &str) -> bool {
+    candidate.len() == token.len()
+        && candidate
+            .bytes()
+            .zip(token.bytes())
+            .fold(0u8, |acc, (a, b)| acc | (a ^ b))
+            == 0
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum ByteRange {
+    Full,
+    /// Inclusive byte range
+    Partial {
+        start: u64,
+        end: u64,
+    },
+    Unsatisfiable,
+}
+
+/// Parse a single `bytes=` range; anything else is served in full
+fn parse_range(header: Option<&str>, len: u64) -> ByteRange {
+    let Some(spec) = header.and_then(|h| h.trim().strip_prefix("bytes=")) else {
+        return ByteRange::Full;
+    };
+    if spec.contains(',') {
+        return ByteRange::Full;
+    }
+    let Some((start, end)) = spec.split_once('-') else {
+        return ByteRange::Full;
+    };
+    let (start, end) = (start.trim(), end.trim());
+
+    if start.is_empty() {
+        let Ok(suffix) = end.parse::<u64>() else {
+            return ByteRange::Full;
+        };
+        if suffix == 0 || len == 0 {
+            return ByteRange::Unsatisfiable;
+        }
+        return ByteRange::Partial {
+            start: len - suffix.min(len),
+            end: len - 1,
+        };
+    }
+
+    let Ok(start) = start.parse::<u64>() else {
+        return ByteRange::Full;
+    };
+    if start >= len {
+        return ByteRange::Unsatisfiable;
+    }
+    let end = if end.is_empty() {
+        len - 1
+    } else {
+        match end.parse::<u64>() {
+            Ok(end) if end >= start => end.min(len - 1),
+            _ => return ByteRange::Full,
+        }
+    };
+    ByteRange::Partial { start, end }
+}
+
+fn percent_decode(input: &str) -> Option<String> {
+    let bytes = input.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let hex = std::str::from_utf8(bytes.get(i + 1..i + 3)?).ok()?;
+            decoded.push(u8::from_str_radix(hex, 16).ok()?);
+            i += 3;
+        } else {
+            decoded.push(bytes[i]);
+            i += 1;
+        }
+    }
+    String::from_utf8(decoded).ok()
+}
+
+fn percent_encode(path: &str) -> String {
+    let mut encoded = String::with_capacity(path.len());
+    for byte in path.bytes() {
+        if byte.is_ascii_alphanumeric() || b"-._~/".contains(&byte) {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{byte:02X}"));
+        }
+    }
+    encoded
+}
+
+fn html_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn content_type(path: &str) -> &'static str {
+    let extension = path
+        .rsplit_once('.')
+        .map(|(_, ext)| ext.to_ascii_lowercase())
+        .unwrap_or_default();
+    match extension.as_str() {
+        "txt" | "md" | "log" => "text/plain; charset=utf-8",
+        "csv" => "text/csv; charset=utf-8",
+        "html" | "htm" => "text/html; charset=utf-8",
+        "json" => "application/json",
+        "pdf" => "application/pdf",
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        _ => "application/octet-stream",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::snapshot_store::SnapshotLimits;
+    use super::super::test_archive;
+    use super::*;
+
+    const TOKEN: &str = "0123456789abcdef0123456789abcdef";
+
+    fn server() -> BrowseServer {
+        let archive = test_archive(&[
+            ("Family.manifest", b"{}"),
+            ("docs/will.pdf", b"0123456789"),
+            ("docs/my letter.txt", b"hello"),
+        ]);
+        let store = SnapshotStore::from_archive(
+            &archive,
+            |path| path.starts_with("docs/"),
+            SnapshotLimits::default(),
+        )
+        .unwrap();
+        BrowseServer::start(Arc::new(store), TOKEN.to_string()).unwrap()
+    }
+
+    fn send(server: &BrowseServer, request_line: &str, headers: &str) -> (u16, String, Vec<u8>) {
+        let mut stream = TcpStream::connect(server.local_addr()).unwrap();
+        write!(
+            stream,
+            "{request_line} HTTP/1.1\r\nHost: {}\r\n{headers}\r\n",
+            server.local_addr()
+        )
+        .unwrap();
+
+        let mut raw = Vec::new();
+        stream.read_to_end(&mut raw).unwrap();
+        let split = raw.windows(4).position(|w| w == b"\r\n\r\n").unwrap();
+        let head = String::from_utf8(raw[..split].to_vec()).unwrap();
+        let status = head[9..12].parse().unwrap();
+        (status, head, raw[split + 4..].to_vec())
+    }
+
+    #[test]
+    fn test_binds_loopback_only() {
+        let server = server();
+        assert!(server.local_addr().ip().is_loopback());
+        assert!(server.url().starts_with("http://127.0.0.1:"));
+        assert!(server.url().ends_with(&format!("/{TOKEN}/")));
+    }
+
+    #[test]
+    fn test_token_is_required() {
+        let server = server();
+
+        assert_eq!(send(&server, "GET /", "").0, 404);
+        assert_eq!(send(&server, "GET /docs/will.pdf", "").0, 404);
+        let wrong = format!("GET /{}/docs/will.pdf", "f".repeat(TOKEN.len()));
+        assert_eq!(send(&server, &wrong, "").0, 404);
+
+        let (status, _, body) = send(&server, &format!("GET /{TOKEN}/docs/will.pdf"), "");
+        assert_eq!(status, 200);
+        assert_eq!(body, b"0123456789");
+    }
+
+    #[test]
+    fn test_index_lists_only_snapshot_files() {
+        let server = server();
+        let (status, _, body) = send(&server, &format!("GET /{TOKEN}/"), "");
+        let html = String::from_utf8(body).unwrap();
+
+        assert_eq!(status, 200);
+        assert!(html.contains("href=\"docs/my%20letter.txt\""));
+        assert!(html.contains("href=\"docs/will.pdf\""));
+        assert!(!html.contains("Family.manifest"));
+
+        let (status, _, body) = send(&server, &format!("GET /{TOKEN}/docs/my%20letter.txt"), "");
+        assert_eq!(status, 200);
+        assert_eq!(body, b"hello");
+        assert_eq!(
+            send(&server, &format!("GET /{TOKEN}/Family.manifest"), "").0,
+            404
+        );
+    }
+
+    #[test]
+    fn test_range_requests() {
+        let server = server();
+        let target = format!("GET /{TOKEN}/docs/will.pdf");
+
+        let (status, head, body) = send(&server, &target, "Range: bytes=2-4\r\n");
+        assert_eq!(status, 206);
+        assert!(head.contains("Content-Range: bytes 2-4/10"));
+        assert_eq!(body, b"234");
+
+        let (status, _, body) = send(&server, &target, "Range: bytes=-3\r\n");
+        assert_eq!(status, 206);
+        assert_eq!(body, b"789");
+
+        let (status, head, _) = send(&server, &target, "Range: bytes=20-\r\n");
+        assert_eq!(status, 416);
+        assert!(head.contains("Content-Range: bytes */10"));
+    }
+
+    #[test]
+    fn test_rejects_writes_and_foreign_hosts() {
+        let server = server();
+
+        let (status, head, _) = send(&server, &format!("PUT /{TOKEN}/docs/new.txt"), "");
+        assert_eq!(status, 405);
+        assert!(head.contains("Allow: GET, HEAD"));
+
+        let mut stream = TcpStream::connect(server.local_addr()).unwrap();
+        write!(
+            stream,
+            "GET /{TOKEN}/ HTTP/1.1\r\nHost: attacker.example\r\n\r\n"
+        )
+        .unwrap();
+        let mut raw = String::new();
+        stream.read_to_string(&mut raw).unwrap();
+        assert!(raw.starts_with("HTTP/1.1 403"));
+    }
+
+    #[test]
+    fn test_head_omits_body() {
+        let server = server();
+        let (status, head, body) = send(&server, &format!("HEAD /{TOKEN}/docs/will.pdf"), "");
+        assert_eq!(status, 200);
+        assert!(head.contains("Content-Length: 10"));
+        assert!(body.is_empty());
+    }
+
+    #[test]
+    fn test_stop_closes_listener() {
+        let mut server = server();
+        let addr = server.local_addr();
+        server.stop();
+        assert!(TcpStream::connect(addr).is_err());
+    }
+
+    #[test]
+    fn test_parse_range() {
+        assert_eq!(parse_range(None, 10), ByteRange::Full);
+        assert_eq!(parse_range(Some("bytes=0-1,4-5"), 10), ByteRange::Full);
+        assert_eq!(parse_range(Some("bytes=5-2"), 10), ByteRange::Full);
+        assert_eq!(
+            parse_range(Some("bytes=5-"), 10),
+            ByteRange::Partial { start: 5, end: 9 }
+        );
+        assert_eq!(
+            parse_range(Some("bytes=-50"), 10),
+            ByteRange::Partial { start: 0, end: 9 }
+        );
+        assert_eq!(
+            parse_range(Some("bytes=8-50"), 10),
+            ByteRange::Partial { start: 8, end: 9 }
+        );
+        assert_eq!(parse_range(Some("bytes=0-"), 0), ByteRange::Unsatisfiable);
+    }
+}
@@ -0,0 +1,328 @@
+//! Decrypted archive snapshot held for read-only browsing
+//!
+//! Files are unpacked from the decrypted tar.gz into zeroizing memory
+//! buffers. Once the memory budget is used, further files spill to a 0600
+//! temporary file that is overwritten before it is deleted. Only entries the
+//! caller marks visible (the manifest-known files) are kept.
+
+use crate::prelude::*;
+use crate::services::shared::infrastructure::SecureTempFile;
+use flate2::read::GzDecoder;
+use std::collections::BTreeMap;
+use std::fs::{File, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::sync::{Mutex, MutexGuard};
+use zeroize::Zeroizing;
+
+/// Default memory budget before files spill to disk
+pub const DEFAULT_SNAPSHOT_MEMORY_BYTES: u64 = 256 * 1024 * 1024;
+/// Default cap on spilled bytes; larger archives cannot be browsed
+pub const DEFAULT_SNAPSHOT_SPILL_BYTES: u64 = 4 * 1024 * 1024 * 1024;
+
+/// Size bounds for a snapshot
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SnapshotLimits {
+    /// Bytes kept in memory before spilling to a temporary file
+    pub memory_bytes: u64,
+    /// Bytes allowed in the temporary file; zero disables spilling
+    pub spill_bytes: u64,
+}
+
+impl Default for SnapshotLimits {
+    fn default() -> Self {
+        Self {
+            memory_bytes: DEFAULT_SNAPSHOT_MEMORY_BYTES,
+            spill_bytes: DEFAULT_SNAPSHOT_SPILL_BYTES,
+        }
+    }
+}
+
+enum Contents {
+    Memory(Zeroizing<Vec<u8>>),
+    Spilled { offset: u64 },
+}
+
+struct StoredFile {
+    len: u64,
+    contents: Contents,
+}
+
+struct SpillFile {
+    temp: SecureTempFile,
+    file: File,
+    len: u64,
+}
+
+#[derive(Default)]
+struct StoreState {
+    files: BTreeMap<String, StoredFile>,
+    spill: Option<SpillFile>,
+    memory_bytes: u64,
+    wiped: bool,
+}
+
+/// Read-only set of decrypted files, addressed by archive path
+pub struct SnapshotStore {
+    state: Mutex<StoreState>,
+}
+
+impl std::fmt::Debug for SnapshotStore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let state = self.lock_state();
+        f.debug_struct("SnapshotStore")
+            .field("files", &state.files.len())
+            .field("memory_bytes", &state.memory_bytes)
+            .field("spilled_bytes", &state.spill.as_ref().map_or(0, |s| s.len))
+            .field("wiped", &state.wiped)
+            .finish()
+    }
+}
+
+impl SnapshotStore {
+    /// Unpack the visible regular files of a decrypted tar.gz archive
+    ///
+    /// Fails if the visible files exceed both the memory and spill budgets.
+    pub fn from_archive(
+        archive: &[u8],
+        is_visible: impl Fn(&str) -> bool,
+        limits: SnapshotLimits,
+    ) -> io::Result<Self> {
+        let mut state = StoreState::default();
+        let mut tar = tar::Archive::new(GzDecoder::new(archive));
+
+        for entry in tar.entries()? {
+            let mut entry = entry?;
+            if !entry.header().entry_type().is_file() {
+                continue;
+            }
+
+            let path = entry.path()?.to_string_lossy().replace('\\', "/");
+            let path = path.trim_start_matches("./").to_string();
+            if !is_visible(&path) {
+                continue;
+            }
+
+            let len = entry.header().size()?;
+            let contents = if state.memory_bytes + len <= limits.memory_bytes {
+                // Exact capacity so the buffer never reallocates and leaves
+                // unzeroized copies behind
+                let mut buffer = Zeroizing::new(Vec::with_capacity(len as usize));
+                (&mut entry).take(len).read_to_end(&mut buffer)?;
+                state.memory_bytes += len;
+                Contents::Memory(buffer)
+            } else {
+                let spilled = state.spill.as_ref().map_or(0, |s| s.len);
+                if spilled + len > limits.spill_bytes {
+                    return Err(io::Error::new(
+                        io::ErrorKind::FileTooLarge,
+                        format!(
+                            "Archive is too large to browse (limit {} bytes in memory, {} on disk)",
+                            limits.memory_bytes, limits.spill_bytes
+                        ),
+                    ));
+                }
+                let offset = state.spill(&mut entry, len)?;
+                Contents::Spilled { offset }
+            };
+
+            state.files.insert(path, StoredFile { len, contents });
+        }
+
+        debug!(
+            files = state.files.len(),
+            memory_bytes = state.memory_bytes,
+            spilled_bytes = state.spill.as_ref().map_or(0, |s| s.len),
+            "Loaded archive snapshot"
+        );
+
+        Ok(Self {
+            state: Mutex::new(state),
+        })
+    }
+
+    /// Paths of all files in the snapshot, sorted
+    pub fn paths(&self) -> Vec<String> {
+        self.lock_state().files.keys().cloned().collect()
+    }
+
+    pub fn file_count(&self) -> usize {
+        self.lock_state().files.len()
+    }
+
+    /// Length of a file, if the snapshot has it
+    pub fn file_len(&self, path: &str) -> Option<u64> {
+        self.lock_state().files.get(path).map(|f| f.len)
+    }
+
+    /// Read up to `max_len` bytes of a file starting at `start`
+    pub fn read(&self, path: &str, start: u64, max_len: u64) -> io::Result<Vec<u8>> {
+        let mut state = self.lock_state();
+        let StoreState { files, spill, .. } = &mut *state;
+        let file = files
+            .get(path)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "Not in snapshot"))?;
+
+        let start = start.min(file.len);
+        let len = max_len.min(file.len - start) as usize;
+        match &file.contents {
+            Contents::Memory(buffer) => Ok(buffer[start as usize..start as usize + len].to_vec()),
+            Contents::Spilled { offset } => {
+                let spill = spill
+                    .as_mut()
+                    .ok_or_else(|| io::Error::other("Spill file missing"))?;
+                let mut chunk = vec![0; len];
+                spill.file.seek(SeekFrom::Start(offset + start))?;
+                spill.file.read_exact(&mut chunk)?;
+                Ok(chunk)
+            }
+        }
+    }
+
+    /// Bytes of file content held in memory
+    pub fn memory_bytes(&self) -> u64 {
+        self.lock_state().memory_bytes
+    }
+
+    /// Bytes of file content written to the spill file
+    pub fn spilled_bytes(&self) -> u64 {
+        self.lock_state().spill.as_ref().map_or(0, |s| s.len)
+    }
+
+    pub fn is_wiped(&self) -> bool {
+        self.lock_state().wiped
+    }
+
+    /// Zero and release every buffer and securely delete the spill file
+    ///
+    /// Later reads fail as if the file never existed.
+    pub fn wipe(&self) {
+        let mut state = self.lock_state();
+        if state.wiped {
+            return;
+        }
+
+        // Zeroizing buffers clear themselves as they drop
+        state.files.clear();
+        state.memory_bytes = 0;
+        state.wiped = true;
+
+        if let Some(spill) = state.spill.take() {
+            drop(spill.file);
+            if let Err(e) = spill.temp.secure_delete() {
+                warn!(error = %e, "Failed to securely delete snapshot spill file");
+            }
+        }
+    }
+
+    fn lock_state(&self) -> MutexGuard<'_, StoreState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl StoreState {
+    /// Append a file to the spill file, creating it on first use
+    fn spill(&mut self, reader: &mut impl Read, len: u64) -> io::Result<u64> {
+        if self.spill.is_none() {
+            let temp = SecureTempFile::new().map_err(|e| io::Error::other(e.to_string()))?;
+            let file = OpenOptions::new()
+                .read(true)
+                .write(true)
+                .open(temp.path())?;
+            debug!("Snapshot exceeded memory budget, spilling to temporary file");
+            self.spill = Some(SpillFile { temp, file, len: 0 });
+        }
+
+        let spill = self.spill.as_mut().expect("spill file created above");
+        let offset = spill.len;
+        spill.file.seek(SeekFrom::Start(offset))?;
+        let copied = io::copy(&mut reader.take(len), &mut spill.file)?;
+        spill.file.flush()?;
+        spill.len += copied;
+        Ok(offset)
+    }
+}
+
+impl Drop for SnapshotStore {
+    fn drop(&mut self) {
+        self.wipe();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::test_archive as tar_gz;
+    use super::*;
+
+    fn sample() -> Vec<u8> {
+        tar_gz(&[
+            ("Family.manifest", b"{}"),
+            ("docs/will.pdf", b"0123456789"),
+            ("docs/letter.txt", b"hello"),
+        ])
+    }
+
+    #[test]
+    fn test_only_visible_files_are_kept() {
+        let store = SnapshotStore::from_archive(
+            &sample(),
+            |path| path.starts_with("docs/"),
+            SnapshotLimits::default(),
+        )
+        .unwrap();
+
+        assert_eq!(store.paths(), ["docs/letter.txt", "docs/will.pdf"]);
+        assert_eq!(store.file_len("Family.manifest"), None);
+        assert_eq!(store.read("docs/will.pdf", 2, 3).unwrap(), b"234");
+        assert_eq!(store.read("docs/will.pdf", 8, 100).unwrap(), b"89");
+    }
+
+    #[test]
+    fn test_spills_past_memory_budget() {
+        let limits = SnapshotLimits {
+            memory_bytes: 6,
+            spill_bytes: 1024,
+        };
+        let store =
+            SnapshotStore::from_archive(&sample(), |p| p.starts_with("docs/"), limits).unwrap();
+
+        assert_eq!(store.memory_bytes() + store.spilled_bytes(), 15);
+        assert!(store.spilled_bytes() > 0);
+        assert_eq!(store.read("docs/will.pdf", 0, 100).unwrap(), b"0123456789");
+        assert_eq!(store.read("docs/letter.txt", 1, 3).unwrap(), b"ell");
+    }
+
+    #[test]
+    fn test_rejects_archive_over_both_budgets() {
+        let limits = SnapshotLimits {
+            memory_bytes: 6,
+            spill_bytes: 4,
+        };
+        let result = SnapshotStore::from_archive(&sample(), |p| p.starts_with("docs/"), limits);
+        assert_eq!(result.unwrap_err().kind(), io::ErrorKind::FileTooLarge);
+    }
+
+    #[test]
+    fn test_wipe_releases_everything() {
+        let limits = SnapshotLimits {
+            memory_bytes: 6,
+            spill_bytes: 1024,
+        };
+        let store =
+            SnapshotStore::from_archive(&sample(), |p| p.starts_with("docs/"), limits).unwrap();
+        let spill_path = store
+            .lock_state()
+            .spill
+            .as_ref()
+            .map(|s| s.temp.path().to_path_buf())
+            .unwrap();
+        assert!(spill_path.exists());
+
+        store.wipe();
+
+        assert!(store.is_wiped());
+        assert_eq!(store.file_count(), 0);
+        assert_eq!(store.memory_bytes() + store.spilled_bytes(), 0);
+        assert!(!spill_path.exists());
+        assert!(store.read("docs/letter.txt", 0, 5).is_err());
+    }
+}
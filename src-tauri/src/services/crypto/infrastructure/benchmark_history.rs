@@ -0,0 +1,131 @@
+//! Benchmark history
+//!
+//! Keeps recent benchmark results so runs on this device can be compared
+//! over time. Stored as a single JSON file in the config directory, oldest
+//! results dropped first.
+
+use crate::services::crypto::domain::models::BenchmarkResult;
+use crate::services::shared::infrastructure::io::atomic_write_sync;
+use crate::services::shared::infrastructure::path_management::get_config_dir;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tracing::debug;
+
+const HISTORY_FILENAME: &str = "benchmark_history.json";
+const HISTORY_SCHEMA: &str = "barqly.vault.benchmark-history/1";
+/// Results kept in the history
+pub const BENCHMARK_HISTORY_LIMIT: usize = 50;
+
+/// Benchmark results recorded on this device, oldest first
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BenchmarkHistory {
+    pub schema: String,
+    #[serde(default)]
+    pub results: Vec<BenchmarkResult>,
+}
+
+impl Default for BenchmarkHistory {
+    fn default() -> Self {
+        Self {
+            schema: HISTORY_SCHEMA.to_string(),
+            results: Vec::new(),
+        }
+    }
+}
+
+impl BenchmarkHistory {
+    fn get_history_path() -> Result<PathBuf, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(get_config_dir()?.join(HISTORY_FILENAME))
+    }
+
+    /// Load the history from the config directory (empty if none saved yet)
+    pub fn load() -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        Self::load_from(&Self::get_history_path()?)
+    }
+
+    /// Add a result to the history in the config directory
+    pub fn append(result: BenchmarkResult) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        Self::append_to(&Self::get_history_path()?, result)
+    }
+
+    pub fn load_from(path: &Path) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        if !path.exists() {
+            debug!("Benchmark history doesn't exist, starting empty");
+            return Ok(Self::default());
+        }
+
+        let content = fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&content)?)
+    }
+
+    pub fn append_to(
+        path: &Path,
+        result: BenchmarkResult,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let mut history = Self::load_from(path)?;
+        history.results.push(result);
+        let excess = history
+            .results
+            .len()
+            .saturating_sub(BENCHMARK_HISTORY_LIMIT);
+        history.results.drain(..excess);
+
+        let json = serde_json::to_string_pretty(&history)?;
+        atomic_write_sync(path, json.as_bytes())?;
+        debug!(results = history.results.len(), "Saved benchmark history");
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::crypto::domain::models::{BenchmarkProfile, BenchmarkSizes};
+    use crate::types::{ByteSize, DurationMs};
+    use chrono::Utc;
+    use tempfile::TempDir;
+
+    fn result(id: usize) -> BenchmarkResult {
+        let sizes = BenchmarkSizes::for_profile(BenchmarkProfile::Quick);
+        BenchmarkResult {
+            benchmark_id: format!("benchmark_{id}"),
+            profile: BenchmarkProfile::Quick,
+            sizes,
+            started_at: Utc::now(),
+            file_count: sizes.small_file_count + 1,
+            input_size: sizes.total(),
+            archive_size: sizes.total(),
+            phases: Vec::new(),
+            encrypt_duration_ms: DurationMs(10),
+            decrypt_duration_ms: DurationMs(10),
+            total_duration_ms: DurationMs(30),
+            peak_memory: Some(ByteSize(1024)),
+        }
+    }
+
+    #[test]
+    fn test_append_keeps_most_recent_results() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join(HISTORY_FILENAME);
+        assert!(
+            BenchmarkHistory::load_from(&path)
+                .unwrap()
+                .results
+                .is_empty()
+        );
+
+        for id in 0..BENCHMARK_HISTORY_LIMIT + 2 {
+            BenchmarkHistory::append_to(&path, result(id)).unwrap();
+        }
+
+        let history = BenchmarkHistory::load_from(&path).unwrap();
+        assert_eq!(history.schema, HISTORY_SCHEMA);
+        assert_eq!(history.results.len(), BENCHMARK_HISTORY_LIMIT);
+        assert_eq!(history.results[0].benchmark_id, "benchmark_2");
+        assert_eq!(
+            history.results.last().unwrap().benchmark_id,
+            format!("benchmark_{}", BENCHMARK_HISTORY_LIMIT + 1)
+        );
+    }
+}
@@ -0,0 +1,70 @@
+//! Cleanup session store
+//!
+//! Pending cleanups of decrypted output, kept as a single JSON file in the
+//! config directory so a scheduled deletion survives an app restart.
+
+use crate::services::crypto::domain::models::CleanupSession;
+use crate::services::shared::infrastructure::io::atomic_write_sync;
+use crate::services::shared::infrastructure::path_management::get_config_dir;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tracing::debug;
+
+const SESSIONS_FILENAME: &str = "cleanup_sessions.json";
+const SESSIONS_SCHEMA: &str = "barqly.vault.cleanup-sessions/1";
+
+/// Pending cleanup sessions, in registration order
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CleanupSessionStore {
+    pub schema: String,
+    #[serde(default)]
+    pub sessions: Vec<CleanupSession>,
+}
+
+impl Default for CleanupSessionStore {
+    fn default() -> Self {
+        Self {
+            schema: SESSIONS_SCHEMA.to_string(),
+            sessions: Vec::new(),
+        }
+    }
+}
+
+impl CleanupSessionStore {
+    pub fn get_store_path() -> Result<PathBuf, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(get_config_dir()?.join(SESSIONS_FILENAME))
+    }
+
+    /// Load the sessions saved at `path` (empty if none saved yet)
+    pub fn load_from(path: &Path) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        if !path.exists() {
+            debug!("Cleanup sessions file doesn't exist, starting empty");
+            return Ok(Self::default());
+        }
+
+        let content = fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&content)?)
+    }
+
+    pub fn save_to(&self, path: &Path) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let json = serde_json::to_string_pretty(self)?;
+        atomic_write_sync(path, json.as_bytes())?;
+        debug!(sessions = self.sessions.len(), "Saved cleanup sessions");
+        Ok(())
+    }
+
+    pub fn get(&self, id: &str) -> Option<&CleanupSession> {
+        self.sessions.iter().find(|s| s.id == id)
+    }
+
+    pub fn get_mut(&mut self, id: &str) -> Option<&mut CleanupSession> {
+        self.sessions.iter_mut().find(|s| s.id == id)
+    }
+
+    /// Remove a session, returning it if it was pending
+    pub fn remove(&mut self, id: &str) -> Option<CleanupSession> {
+        let index = self.sessions.iter().position(|s| s.id == id)?;
+        Some(self.sessions.remove(index))
+    }
+}
@@ -2,16 +2,46 @@
 //!
 //! Provides technical implementations for cryptographic operations using the age encryption standard.
 
+pub mod age_armor;
+pub mod age_header;
 pub mod age_operations;
+pub mod age_salvage;
+pub mod archive_browse;
+pub mod benchmark_history;
+pub mod cleanup_sessions;
 pub mod crypto_errors;
 pub mod multi_recipient_encryption;
 
 // Re-export main operations
 pub use age_operations::{
     decrypt_data, decrypt_data_cli, decrypt_data_yubikey_cli, encrypt_data,
-    encrypt_data_multi_recipient,
+    encrypt_data_multi_recipient, open_yubikey_decrypt_session,
 };
 
+// Re-export armored archive handling
+pub use age_armor::{
+    ArchivePreamble, ArmoredOutputOptions, archive_reader, armor_archive, read_age_archive,
+    read_archive_preamble,
+};
+
+// Re-export header inspection
+pub use age_header::{
+    AgeHeader, HeaderStanza, is_x25519_recipient, read_age_header, read_age_header_file,
+    yubikey_recipient_tag,
+};
+
+// Re-export damaged-archive salvage
+pub use age_salvage::{ByteRange, STREAM_CHUNK_SIZE, SalvagedPayload, salvage_payload};
+
+// Re-export archive browsing
+pub use archive_browse::{BrowseServer, SnapshotLimits, SnapshotStore};
+
+// Re-export benchmark history
+pub use benchmark_history::BenchmarkHistory;
+
+// Re-export the cleanup session store
+pub use cleanup_sessions::CleanupSessionStore;
+
 // Re-export types
 pub use age_operations::{KeyPair, PrivateKey, PublicKey};
 
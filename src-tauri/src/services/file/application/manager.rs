@@ -1,12 +1,16 @@
-use super::services::{ArchiveService, ManifestService};
+use super::services::{ArchiveService, ManifestService, UploadMetadataService};
 use crate::services::file::domain::FileResult;
-use crate::services::file::domain::models::{FileInfo, FileSelection, Manifest};
-use crate::services::file::infrastructure::file_operations::ArchiveOperation;
-use std::path::PathBuf;
+use crate::services::file::domain::models::{FileInfo, FileSelection, Manifest, UploadMetadata};
+use crate::services::file::infrastructure::file_operations::archive_manifest::ManifestSummary;
+use crate::services::file::infrastructure::file_operations::{
+    ArchiveOperation, UnexpectedEntries, VerificationStrictness,
+};
+use std::path::{Path, PathBuf};
 
 pub struct FileManager {
     archive_service: ArchiveService,
     manifest_service: ManifestService,
+    upload_metadata_service: UploadMetadataService,
 }
 
 impl FileManager {
@@ -14,6 +18,7 @@ pub fn new() -> Self {
         Self {
             archive_service: ArchiveService::new(),
             manifest_service: ManifestService::new(),
+            upload_metadata_service: UploadMetadataService::new(),
         }
     }
 
@@ -53,9 +58,26 @@ pub async fn verify_manifest(
         &self,
         manifest_path: String,
         extracted_files_dir: String,
-    ) -> FileResult<bool> {
+        strictness: VerificationStrictness,
+    ) -> FileResult<Option<UnexpectedEntries>> {
         self.manifest_service
-            .verify_manifest(manifest_path, extracted_files_dir)
+            .verify_manifest(manifest_path, extracted_files_dir, strictness)
+            .await
+    }
+
+    /// Entry count and total size of a saved manifest
+    pub fn manifest_summary(&self, manifest_path: &str) -> FileResult<ManifestSummary> {
+        self.manifest_service.manifest_summary(manifest_path)
+    }
+
+    /// Compute resumable-upload metadata (checksums, multipart ETag) for an archive
+    pub async fn compute_upload_metadata(
+        &self,
+        archive_path: &Path,
+        part_size_mb: u64,
+    ) -> FileResult<UploadMetadata> {
+        self.upload_metadata_service
+            .compute_upload_metadata(archive_path, part_size_mb)
             .await
     }
 }
@@ -4,6 +4,7 @@
 use crate::services::file::infrastructure::file_operations::{
     self as file_operations, ArchiveOperation,
 };
+use crate::types::ByteSize;
 use std::path::PathBuf;
 
 pub struct ArchiveService;
@@ -58,7 +59,7 @@ pub async fn get_file_info(&self, paths: Vec<String>) -> FileResult<Vec<FileInfo
                         .unwrap_or_default()
                         .to_string_lossy()
                         .to_string(),
-                    size,
+                    size: ByteSize(size),
                     is_file: metadata.is_file(),
                     is_directory: metadata.is_dir(),
                     file_count,
@@ -77,7 +78,7 @@ pub async fn create_archive(
     ) -> FileResult<ArchiveOperation> {
         // Validate selection meets business rules
         FileRules::validate_file_paths(&selection.paths)?;
-        FileRules::validate_total_size(selection.total_size)?;
+        FileRules::validate_total_size(selection.total_size.bytes())?;
 
         // Convert command types to file_operations types using canonical method
         let path_bufs: Vec<PathBuf> = selection.paths.iter().map(PathBuf::from).collect();
@@ -120,7 +121,7 @@ pub async fn select_files(&self, selection_type: &str) -> FileResult<FileSelecti
 
             return Ok(FileSelection {
                 paths: vec![],
-                total_size: 0,
+                total_size: ByteSize::ZERO,
                 file_count: 0,
                 selection_type: selection_type_str.to_string(),
             });
@@ -161,7 +162,7 @@ pub async fn select_files(&self, selection_type: &str) -> FileResult<FileSelecti
 
         Ok(FileSelection {
             paths,
-            total_size,
+            total_size: ByteSize(total_size),
             file_count,
             selection_type: selection_type_str.to_string(),
         })
@@ -1,8 +1,11 @@
 use crate::prelude::*;
 use crate::services::file::domain::FileResult;
 use crate::services::file::domain::models::{FileInfo, Manifest};
-use crate::services::file::infrastructure::file_operations::{self as file_ops, FileOpsConfig};
+use crate::services::file::infrastructure::file_operations::{
+    self as file_ops, FileOpsConfig, UnexpectedEntries, VerificationStrictness,
+};
 use crate::services::shared::infrastructure::error::ErrorHandler;
+use crate::types::ByteSize;
 
 pub struct ManifestService;
 
@@ -81,7 +84,7 @@ pub async fn create_manifest(&self, file_paths: Vec<String>) -> FileResult<Manif
                     .unwrap_or_default()
                     .to_string_lossy()
                     .to_string(),
-                size: entry.size,
+                size: ByteSize(entry.size),
                 is_file: true,
                 is_directory: false,
                 file_count: None,
@@ -92,7 +95,7 @@ pub async fn create_manifest(&self, file_paths: Vec<String>) -> FileResult<Manif
             version: file_ops_manifest.version,
             created_at: file_ops_manifest.created.to_rfc3339(),
             files: command_files,
-            total_size: file_ops_manifest.archive.total_uncompressed_size,
+            total_size: ByteSize(file_ops_manifest.archive.total_uncompressed_size),
             file_count: file_ops_manifest.archive.file_count,
         };
 
@@ -104,7 +107,7 @@ pub async fn create_manifest(&self, file_paths: Vec<String>) -> FileResult<Manif
         // Log operation completion
         info!(
             file_count = command_manifest.file_count,
-            total_size = command_manifest.total_size,
+            total_size = command_manifest.total_size.bytes(),
             "Manifest created successfully"
         );
 
@@ -112,77 +115,41 @@ pub async fn create_manifest(&self, file_paths: Vec<String>) -> FileResult<Manif
     }
 
     /// Verify manifest against extracted files
+    ///
+    /// Listed files must match; with `ExactMatch`, anything else found in the
+    /// directory is returned for the caller to report.
     pub async fn verify_manifest(
         &self,
         manifest_path: String,
         extracted_files_dir: String,
-    ) -> FileResult<bool> {
+        strictness: VerificationStrictness,
+    ) -> FileResult<Option<UnexpectedEntries>> {
         use std::path::Path;
 
-        // Load manifest
-        let manifest = file_ops::archive_manifest::Manifest::load(Path::new(&manifest_path))
-            .map_err(|e| {
-                crate::services::file::domain::FileError::ValidationFailed(e.to_string())
-            })?;
+        let manifest_path = Path::new(&manifest_path);
+        let extracted_dir = Path::new(&extracted_files_dir);
 
-        // Get extracted file info
-        let extracted_files = self.get_extracted_files_info(&extracted_files_dir)?;
-
-        // Verify
-        file_ops::verify_manifest(
-            &manifest,
-            &extracted_files,
-            &file_ops::FileOpsConfig::default(),
-        )
-        .map(|_| true)
-        .map_err(|e| crate::services::file::domain::FileError::ValidationFailed(e.to_string()))
-    }
+        // Streams the manifest rather than loading it
+        file_ops::verify_listed_files(manifest_path, extracted_dir).map_err(|e| {
+            crate::services::file::domain::FileError::ValidationFailed(e.to_string())
+        })?;
 
-    /// Get file information from extracted directory
-    fn get_extracted_files_info(&self, extracted_dir: &str) -> FileResult<Vec<file_ops::FileInfo>> {
-        use std::fs;
-        #[cfg(unix)]
-        use std::os::unix::fs::PermissionsExt;
-        use std::path::Path;
-        use walkdir::WalkDir;
-
-        let mut file_infos = Vec::new();
-        let extracted_path = Path::new(extracted_dir);
-
-        for entry in WalkDir::new(extracted_path)
-            .into_iter()
-            .filter_map(|e| e.ok())
-            .filter(|e| e.file_type().is_file())
-        {
-            let path = entry.path();
-            let metadata = fs::metadata(path)
-                .map_err(|e| crate::services::file::domain::FileError::IoError(e.to_string()))?;
-
-            let relative_path = path
-                .strip_prefix(extracted_path)
-                .unwrap_or(path)
-                .to_path_buf();
-
-            let hash = file_ops::utils::calculate_file_hash(path)
-                .map_err(|e| crate::services::file::domain::FileError::IoError(e.to_string()))?;
-
-            let file_info = file_ops::FileInfo {
-                path: relative_path,
-                size: metadata.len(),
-                modified: chrono::DateTime::from(
-                    metadata
-                        .modified()
-                        .unwrap_or_else(|_| std::time::SystemTime::now()),
-                ),
-                hash,
-                #[cfg(unix)]
-                permissions: metadata.permissions().mode(),
-            };
-
-            file_infos.push(file_info);
+        if strictness == VerificationStrictness::ListedOnly {
+            return Ok(None);
         }
+        let config = FileOpsConfig::default();
+        file_ops::find_unexpected_entries(manifest_path, extracted_dir, &config.verify_ignore)
+            .map(Some)
+            .map_err(|e| crate::services::file::domain::FileError::IoError(e.to_string()))
+    }
 
-        Ok(file_infos)
+    /// Entry count and total size of a saved manifest, read from its index
+    pub fn manifest_summary(
+        &self,
+        manifest_path: &str,
+    ) -> FileResult<file_ops::archive_manifest::ManifestSummary> {
+        file_ops::archive_manifest::read_manifest_summary(std::path::Path::new(manifest_path))
+            .map_err(|e| crate::services::file::domain::FileError::ValidationFailed(e.to_string()))
     }
 
     /// Helper function - uses canonical FileSelection::from_paths
@@ -1,5 +1,7 @@
 pub mod archive_service;
 pub mod manifest_service;
+pub mod upload_metadata_service;
 
 pub use archive_service::ArchiveService;
 pub use manifest_service::ManifestService;
+pub use upload_metadata_service::UploadMetadataService;
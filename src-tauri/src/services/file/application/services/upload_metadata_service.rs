@@ -0,0 +1,92 @@
+use crate::constants::{BYTES_PER_MB, MAX_UPLOAD_PART_SIZE_MB, MIN_UPLOAD_PART_SIZE_MB};
+use crate::logging::spawn_blocking;
+use crate::prelude::*;
+use crate::services::file::domain::models::UploadMetadata;
+use crate::services::file::domain::{FileError, FileResult};
+use crate::services::file::infrastructure::file_operations::{self as file_ops, FileOpsError};
+use std::path::Path;
+
+pub struct UploadMetadataService;
+
+impl UploadMetadataService {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Compute resumable-upload metadata for an archive
+    ///
+    /// Runs the streaming hash on a blocking thread; cached results are returned
+    /// without touching the archive contents.
+    pub async fn compute_upload_metadata(
+        &self,
+        archive_path: &Path,
+        part_size_mb: u64,
+    ) -> FileResult<UploadMetadata> {
+        if !(MIN_UPLOAD_PART_SIZE_MB..=MAX_UPLOAD_PART_SIZE_MB).contains(&part_size_mb) {
+            return Err(FileError::ValidationFailed(format!(
+                "Part size must be between {MIN_UPLOAD_PART_SIZE_MB} and {MAX_UPLOAD_PART_SIZE_MB} MB"
+            )));
+        }
+
+        if !archive_path.is_file() {
+            return Err(FileError::FileNotFound(
+                archive_path.to_string_lossy().to_string(),
+            ));
+        }
+
+        let path = archive_path.to_path_buf();
+        let part_size_bytes = part_size_mb * BYTES_PER_MB;
+
+        let result =
+            spawn_blocking(move || file_ops::compute_upload_metadata(&path, part_size_bytes))
+                .await
+                .map_err(|e| FileError::IoError(format!("Upload metadata task failed: {e}")))?;
+
+        result.map_err(|e| match e {
+            FileOpsError::FileNotFound { path } => {
+                FileError::FileNotFound(path.to_string_lossy().to_string())
+            }
+            FileOpsError::InvalidSelection { message } => FileError::ValidationFailed(message),
+            other => FileError::IoError(other.to_string()),
+        })
+    }
+}
+
+impl Default for UploadMetadataService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_rejects_out_of_range_part_size() {
+        let service = UploadMetadataService::new();
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("vault.age");
+        std::fs::write(&path, b"data").unwrap();
+
+        assert!(matches!(
+            service.compute_upload_metadata(&path, 0).await,
+            Err(FileError::ValidationFailed(_))
+        ));
+        assert!(matches!(
+            service
+                .compute_upload_metadata(&path, MAX_UPLOAD_PART_SIZE_MB + 1)
+                .await,
+            Err(FileError::ValidationFailed(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_missing_archive() {
+        let service = UploadMetadataService::new();
+        let result = service
+            .compute_upload_metadata(Path::new("/nonexistent/vault.age"), 8)
+            .await;
+        assert!(matches!(result, Err(FileError::FileNotFound(_))));
+    }
+}
@@ -1,5 +1,6 @@
 //! File information model
 
+use crate::types::ByteSize;
 use serde::Serialize;
 
 /// File information
@@ -7,7 +8,7 @@
 pub struct FileInfo {
     pub path: String,
     pub name: String,
-    pub size: u64,
+    pub size: ByteSize,
     pub is_file: bool,
     pub is_directory: bool,
     pub file_count: Option<usize>, // For directories, the number of files inside
@@ -1,12 +1,13 @@
 //! File selection result model
 
+use crate::types::ByteSize;
 use serde::Serialize;
 
 /// File selection result
 #[derive(Debug, Serialize, specta::Type)]
 pub struct FileSelection {
     pub paths: Vec<String>,
-    pub total_size: u64,
+    pub total_size: ByteSize,
     pub file_count: usize,
     pub selection_type: String,
 }
@@ -1,6 +1,7 @@
 //! Manifest model for encrypted archives
 
 use super::FileInfo;
+use crate::types::ByteSize;
 use serde::Serialize;
 
 /// Manifest for encrypted archives
@@ -9,6 +10,6 @@ pub struct Manifest {
     pub version: String,
     pub created_at: String,
     pub files: Vec<FileInfo>,
-    pub total_size: u64,
+    pub total_size: ByteSize,
     pub file_count: usize,
 }
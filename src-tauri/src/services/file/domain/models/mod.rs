@@ -6,11 +6,18 @@
 pub mod file_rules;
 pub mod file_selection;
 pub mod manifest;
+pub mod parity;
 pub mod selection_type;
+pub mod upload_metadata;
 
 // Re-export for convenience
 pub use file_info::FileInfo;
 pub use file_rules::*;
 pub use file_selection::FileSelection;
 pub use manifest::Manifest;
+pub use parity::{
+    ByteRange, MAX_REDUNDANCY_PERCENT, MIN_REDUNDANCY_PERCENT, ParityCheck, ParityInfo,
+    ParityOptions,
+};
 pub use selection_type::SelectionType;
+pub use upload_metadata::{UploadMetadata, UploadPartChecksum};
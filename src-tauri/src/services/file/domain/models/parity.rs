@@ -0,0 +1,66 @@
+//! Archive parity model
+//!
+//! Reed–Solomon parity kept in a sidecar beside an encrypted archive, so
+//! blocks damaged on long-term storage can be found and rebuilt without a key.
+
+use crate::types::ByteSize;
+use serde::{Deserialize, Serialize};
+
+/// Smallest redundancy that can be requested
+pub const MIN_REDUNDANCY_PERCENT: u8 = 1;
+
+/// Largest redundancy that can be requested (parity as large as the archive)
+pub const MAX_REDUNDANCY_PERCENT: u8 = 100;
+
+/// Parity requested for an encryption
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+pub struct ParityOptions {
+    /// Parity size as a percentage of the archive (1-100); damage up to
+    /// about this share of each stretch of the archive can be repaired
+    pub redundancy_percent: u8,
+}
+
+/// Parity written for an archive, as recorded in the archive index
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+pub struct ParityInfo {
+    /// Sidecar file name in the vaults directory (e.g. "Family.age.par")
+    pub file_name: String,
+    pub redundancy_percent: u8,
+    /// Sidecar size, index included
+    pub parity_size: ByteSize,
+}
+
+/// A stretch of an archive file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+pub struct ByteRange {
+    pub offset: ByteSize,
+    pub length: ByteSize,
+}
+
+/// What comparing an archive against its parity found
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+pub struct ParityCheck {
+    /// Archive ranges whose blocks don't match their recorded hashes
+    pub damaged_ranges: Vec<ByteRange>,
+    /// Damaged ranges with too little surviving parity to rebuild them
+    pub unrepairable_ranges: Vec<ByteRange>,
+    /// Parity blocks in the sidecar that are damaged themselves
+    pub damaged_parity_blocks: usize,
+}
+
+impl ParityCheck {
+    /// Neither the archive nor its parity is damaged
+    pub fn is_intact(&self) -> bool {
+        self.damaged_ranges.is_empty() && self.damaged_parity_blocks == 0
+    }
+
+    /// Every damaged archive block can be rebuilt
+    pub fn is_repairable(&self) -> bool {
+        self.unrepairable_ranges.is_empty()
+    }
+
+    /// Total damaged archive bytes
+    pub fn damaged_bytes(&self) -> u64 {
+        self.damaged_ranges.iter().map(|r| r.length.bytes()).sum()
+    }
+}
@@ -0,0 +1,48 @@
+//! Upload metadata model
+//!
+//! Checksums that S3-compatible uploaders need for (multipart) uploads of an
+//! encrypted archive. Barqly Vault never uploads anything itself - these values
+//! are handed to the user's own tooling.
+
+use crate::types::ByteSize;
+use serde::{Deserialize, Serialize};
+
+/// Checksum of a single multipart-upload part
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+pub struct UploadPartChecksum {
+    /// 1-based part number (matches S3 `PartNumber`)
+    pub part_number: u32,
+    /// Offset of this part within the archive
+    pub offset: ByteSize,
+    /// Part size (last part may be smaller)
+    pub size: ByteSize,
+    /// MD5 of the part, hex encoded
+    pub md5_hex: String,
+    /// MD5 of the part, base64 encoded (`Content-MD5` header)
+    pub md5_base64: String,
+}
+
+/// Resumable-upload friendly metadata for an archive
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+pub struct UploadMetadata {
+    /// Archive file name (no directory)
+    pub file_name: String,
+    /// Archive size
+    pub file_size: ByteSize,
+    /// Whole-file SHA-256, hex encoded
+    pub sha256_hex: String,
+    /// Whole-file SHA-256, base64 encoded (`x-amz-checksum-sha256`)
+    pub sha256_base64: String,
+    /// Whole-file MD5, hex encoded (ETag of a single-request PUT)
+    pub md5_hex: String,
+    /// Whole-file MD5, base64 encoded (`Content-MD5` header)
+    pub md5_base64: String,
+    /// Part size used for the multipart layout
+    pub part_size_bytes: ByteSize,
+    /// Per-part checksums in upload order
+    pub parts: Vec<UploadPartChecksum>,
+    /// Composite multipart ETag (`md5(concat(part_md5s))-<part_count>`)
+    pub multipart_etag: String,
+    /// Whether the values were served from the sidecar cache
+    pub from_cache: bool,
+}
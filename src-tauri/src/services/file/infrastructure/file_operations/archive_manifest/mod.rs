@@ -5,6 +5,8 @@
 
 // Module exports
 pub mod operations;
+pub mod restore_target;
+pub mod streaming;
 pub mod types;
 pub mod verification;
 
@@ -12,5 +14,16 @@
 pub use operations::{
     create_manifest_for_archive, create_manifest_from_archive, extract_and_verify_archive,
 };
+pub use restore_target::{
+    DEFAULT_VERIFY_IGNORE, ListedPaths, MAX_REPORTED_UNEXPECTED, UnexpectedEntries,
+    UnexpectedEntry, VerificationStrictness, find_unexpected_entries, scan_unexpected,
+    verify_listed_files,
+};
+pub use streaming::{
+    ManifestIndex, ManifestShard, ManifestSummary, StreamedManifest, read_manifest_shard,
+    read_manifest_summary, stream_manifest_entries, write_indexed_manifest,
+};
 pub use types::{ArchiveManifest, FileManifestEntry, Manifest};
-pub use verification::{calculate_manifest_hash, verify_manifest};
+pub use verification::{
+    calculate_manifest_hash, verify_directories, verify_manifest, verify_manifest_file,
+};
@@ -6,8 +6,9 @@
 use super::super::archive_operations::extract_archive;
 use super::super::utils::calculate_file_hash;
 use super::super::{ArchiveOperation, FileInfo, FileOpsConfig, FileOpsError, Result};
+use super::streaming::{stream_manifest_entries, write_indexed_manifest};
 use super::types::{ArchiveManifest, FileManifestEntry, Manifest};
-use super::verification::calculate_manifest_hash;
+use super::verification::{calculate_manifest_hash, verify_manifest_file};
 use chrono::Utc;
 use std::fs;
 use std::path::Path;
@@ -57,17 +58,11 @@ pub fn new(
     }
 
     /// Save manifest to file
+    ///
+    /// The file opens with an index so counts and sizes can be read without
+    /// parsing the entries (see `streaming`).
     pub fn save(&self, path: &Path) -> Result<()> {
-        let json = serde_json::to_string_pretty(self).map_err(|e| {
-            FileOpsError::ManifestCreationFailed {
-                message: format!("Failed to serialize manifest: {e}"),
-            }
-        })?;
-
-        fs::write(path, json).map_err(|e| FileOpsError::IoError {
-            message: format!("Failed to write manifest file: {e}"),
-            source: e,
-        })?;
+        write_indexed_manifest(self, path)?;
 
         info!("Manifest saved to: {}", path.display());
         Ok(())
@@ -164,17 +159,14 @@ pub fn extract_and_verify_archive(
         output_dir.display()
     );
 
-    // Load manifest
-    let manifest = Manifest::load(manifest_path)?;
-
-    // Verify manifest integrity
-    manifest.verify_integrity()?;
+    // Check manifest integrity before extracting anything
+    stream_manifest_entries(manifest_path, |_| Ok(()))?.verify_integrity()?;
 
     // Extract archive
     let extracted_files = extract_archive(archive_path, output_dir, config)?;
 
     // Verify extracted files against manifest
-    super::verification::verify_manifest(&manifest, &extracted_files, config)?;
+    verify_manifest_file(manifest_path, &extracted_files, config)?;
 
     info!(
         "Archive extraction and verification completed: {} files",
@@ -0,0 +1,440 @@
+//! Restore directory checks against a saved manifest
+//!
+//! Verification works in two directions. Every file the manifest lists must
+//! be present under the restore directory with the recorded size and hash;
+//! in exact-match mode the directory must also hold nothing the manifest
+//! doesn't list, which matters before a prepared drive is handed to someone
+//! else. OS metadata files named in `FileOpsConfig::verify_ignore` don't
+//! count as unexpected.
+//!
+//! Both directions stream: entries are checked as the manifest is read, and
+//! the directory is walked one entry at a time, so memory follows the number
+//! of listed paths, never the size of the tree. Symlinks are never followed.
+//! A link is reported where it stands, and a listed file reached through one
+//! that leads outside the restore directory fails.
+
+use super::super::utils::calculate_file_hash;
+use super::super::{FileOpsError, Result};
+use super::streaming::stream_manifest_entries;
+use super::types::FileManifestEntry;
+use crate::services::shared::infrastructure::path_matches_any;
+use crate::types::ByteSize;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use tracing::info;
+use walkdir::WalkDir;
+
+/// OS metadata ignored by exact-match verification unless configured otherwise
+pub const DEFAULT_VERIFY_IGNORE: &[&str] = &[
+    ".DS_Store",
+    "._*",
+    ".Spotlight-V100",
+    ".Trashes",
+    ".fseventsd",
+    ".TemporaryItems",
+    "Thumbs.db",
+    "desktop.ini",
+    "$RECYCLE.BIN",
+    "System Volume Information",
+];
+
+/// Unexpected entries listed in a report; the rest are only counted
+pub const MAX_REPORTED_UNEXPECTED: usize = 1000;
+
+/// How much of the restore directory verification covers
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+pub enum VerificationStrictness {
+    /// Listed files exist and match; anything else present is ignored
+    #[default]
+    ListedOnly,
+    /// Listed files match and nothing else is present
+    ExactMatch,
+}
+
+/// A file or directory the manifest doesn't list
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, specta::Type)]
+pub struct UnexpectedEntry {
+    /// Relative to the restore directory
+    pub path: String,
+    /// For a directory, everything under it
+    pub size: ByteSize,
+    /// A symbolic link, reported without following it
+    pub symlink: bool,
+}
+
+/// What exact-match verification found beyond the manifest
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, specta::Type)]
+pub struct UnexpectedEntries {
+    pub files: Vec<UnexpectedEntry>,
+    /// Topmost directories holding no listed file
+    pub directories: Vec<UnexpectedEntry>,
+    /// Entries past `MAX_REPORTED_UNEXPECTED`, counted but not listed
+    pub omitted: usize,
+    pub total_size: ByteSize,
+}
+
+impl UnexpectedEntries {
+    pub fn is_empty(&self) -> bool {
+        self.count() == 0
+    }
+
+    /// Unexpected entries found, including omitted ones
+    pub fn count(&self) -> usize {
+        self.files.len() + self.directories.len() + self.omitted
+    }
+
+    fn push(&mut self, entry: UnexpectedEntry, directory: bool) {
+        self.total_size += entry.size;
+        if self.files.len() + self.directories.len() >= MAX_REPORTED_UNEXPECTED {
+            self.omitted += 1;
+        } else if directory {
+            self.directories.push(entry);
+        } else {
+            self.files.push(entry);
+        }
+    }
+}
+
+/// Paths a manifest lists, with every directory above them
+#[derive(Debug, Default)]
+pub struct ListedPaths {
+    files: HashSet<PathBuf>,
+    directories: HashSet<PathBuf>,
+}
+
+impl ListedPaths {
+    pub fn insert(&mut self, path: PathBuf) {
+        for ancestor in path.ancestors().skip(1) {
+            // A known directory already has its ancestors recorded
+            if ancestor.as_os_str().is_empty() || !self.directories.insert(ancestor.to_path_buf()) {
+                break;
+            }
+        }
+        self.files.insert(path);
+    }
+}
+
+/// Check each file a saved manifest lists under `root`, without loading it
+///
+/// Files present but not listed are ignored; see `find_unexpected_entries`.
+pub fn verify_listed_files(manifest_path: &Path, root: &Path) -> Result<()> {
+    let canonical_root = root.canonicalize().map_err(|e| FileOpsError::IoError {
+        message: format!("Failed to resolve {}", root.display()),
+        source: e,
+    })?;
+
+    let mut first_failure = None;
+    let streamed = stream_manifest_entries(manifest_path, |entry| {
+        if first_failure.is_none() {
+            first_failure = check_listed_file(root, &canonical_root, &entry).err();
+        }
+        Ok(())
+    })?;
+
+    streamed.verify_integrity()?;
+    if let Some(message) = first_failure {
+        return Err(FileOpsError::ManifestVerificationFailed { message });
+    }
+
+    info!(
+        "Verified {} listed files in {}",
+        streamed.entry_count,
+        root.display()
+    );
+    Ok(())
+}
+
+/// Files and directories under `root` that a saved manifest doesn't list
+///
+/// Entries whose relative path matches a pattern in `ignore` are skipped,
+/// along with everything under them.
+pub fn find_unexpected_entries(
+    manifest_path: &Path,
+    root: &Path,
+    ignore: &[String],
+) -> Result<UnexpectedEntries> {
+    let mut listed = ListedPaths::default();
+    stream_manifest_entries(manifest_path, |entry| {
+        listed.insert(entry.path);
+        Ok(())
+    })?;
+    scan_unexpected(root, &listed, ignore)
+}
+
+/// Walk `root` for entries not in `listed`
+///
+/// A directory holding no listed file is reported once, sized by its
+/// contents, rather than entry by entry.
+pub fn scan_unexpected(
+    root: &Path,
+    listed: &ListedPaths,
+    ignore: &[String],
+) -> Result<UnexpectedEntries> {
+    let mut report = UnexpectedEntries::default();
+    let mut open_directory: Option<(PathBuf, UnexpectedEntry)> = None;
+
+    let walker = WalkDir::new(root)
+        .follow_links(false)
+        .min_depth(1)
+        .into_iter()
+        .filter_entry(|entry| {
+            let relative = entry.path().strip_prefix(root).unwrap_or(entry.path());
+            !path_matches_any(ignore, &relative.to_string_lossy())
+        });
+
+    for entry in walker {
+        let entry = entry.map_err(|e| FileOpsError::IoError {
+            message: format!("Failed to walk {}", root.display()),
+            source: e.into(),
+        })?;
+        let relative = entry
+            .path()
+            .strip_prefix(root)
+            .unwrap_or(entry.path())
+            .to_path_buf();
+        let file_type = entry.file_type();
+        // Not followed: a link's own size, never its target's
+        let size = if file_type.is_dir() {
+            ByteSize::ZERO
+        } else {
+            ByteSize(entry.metadata().map_or(0, |metadata| metadata.len()))
+        };
+
+        if let Some((directory, unexpected)) = open_directory.as_mut() {
+            if relative.starts_with(directory.as_path()) {
+                unexpected.size += size;
+                continue;
+            }
+            if let Some((_, unexpected)) = open_directory.take() {
+                report.push(unexpected, true);
+            }
+        }
+
+        let unexpected = UnexpectedEntry {
+            path: relative.display().to_string(),
+            size,
+            symlink: file_type.is_symlink(),
+        };
+        if file_type.is_dir() {
+            if !listed.directories.contains(&relative) {
+                open_directory = Some((relative, unexpected));
+            }
+        } else if !(file_type.is_file() && listed.files.contains(&relative)) {
+            report.push(unexpected, false);
+        }
+    }
+    if let Some((_, unexpected)) = open_directory {
+        report.push(unexpected, true);
+    }
+
+    info!(
+        "Found {} unexpected entries in {}",
+        report.count(),
+        root.display()
+    );
+    Ok(report)
+}
+
+fn check_listed_file(
+    root: &Path,
+    canonical_root: &Path,
+    entry: &FileManifestEntry,
+) -> std::result::Result<(), String> {
+    let display = entry.path.display();
+    if !entry
+        .path
+        .components()
+        .all(|component| matches!(component, Component::Normal(_)))
+    {
+        return Err(format!(
+            "Manifest path leaves the restore directory: {display}"
+        ));
+    }
+
+    let path = root.join(&entry.path);
+    let metadata = fs::symlink_metadata(&path)
+        .map_err(|_| format!("File missing from the restore directory: {display}"))?;
+    if !metadata.is_file() {
+        return Err(format!("Not a regular file: {display}"));
+    }
+    let inside = path
+        .canonicalize()
+        .is_ok_and(|resolved| resolved.starts_with(canonical_root));
+    if !inside {
+        return Err(format!("File is outside the restore directory: {display}"));
+    }
+
+    if metadata.len() != entry.size {
+        return Err(format!(
+            "File size mismatch for {display}: manifest has {}, extracted has {}",
+            entry.size,
+            metadata.len()
+        ));
+    }
+    let hash = calculate_file_hash(&path).map_err(|e| e.to_string())?;
+    if hash != entry.hash {
+        return Err(format!(
+            "File hash mismatch for {display}: manifest has {}, extracted has {hash}",
+            entry.hash
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn ignore() -> Vec<String> {
+        DEFAULT_VERIFY_IGNORE
+            .iter()
+            .map(|p| p.to_string())
+            .collect()
+    }
+
+    fn listed(paths: &[&str]) -> ListedPaths {
+        let mut listed = ListedPaths::default();
+        for path in paths {
+            listed.insert(PathBuf::from(path));
+        }
+        listed
+    }
+
+    /// A restore directory holding exactly `docs/will.pdf` and `keys/recovery.txt`
+    fn restore_dir() -> TempDir {
+        let root = TempDir::new().unwrap();
+        fs::create_dir_all(root.path().join("docs")).unwrap();
+        fs::create_dir_all(root.path().join("keys")).unwrap();
+        fs::write(root.path().join("docs/will.pdf"), b"will").unwrap();
+        fs::write(root.path().join("keys/recovery.txt"), b"recovery").unwrap();
+        root
+    }
+
+    fn scan(root: &TempDir) -> UnexpectedEntries {
+        let listed = listed(&["docs/will.pdf", "keys/recovery.txt"]);
+        scan_unexpected(root.path(), &listed, &ignore()).unwrap()
+    }
+
+    #[test]
+    fn test_exact_restore_has_nothing_unexpected() {
+        let root = restore_dir();
+        assert!(scan(&root).is_empty());
+    }
+
+    #[test]
+    fn test_extra_file_and_directories() {
+        let root = restore_dir();
+        fs::write(root.path().join("docs/notes.txt"), b"12345").unwrap();
+        fs::create_dir(root.path().join("empty")).unwrap();
+        fs::create_dir_all(root.path().join("extra/nested")).unwrap();
+        fs::write(root.path().join("extra/nested/a.bin"), vec![0u8; 100]).unwrap();
+        fs::write(root.path().join("extra/b.bin"), vec![0u8; 20]).unwrap();
+
+        let report = scan(&root);
+        assert_eq!(report.files.len(), 1);
+        assert_eq!(report.files[0].size, ByteSize(5));
+        assert!(report.files[0].path.ends_with("notes.txt"));
+
+        // An unlisted tree is reported once, at its top, with its total size
+        let mut directories: Vec<_> = report
+            .directories
+            .iter()
+            .map(|d| (d.path.as_str(), d.size))
+            .collect();
+        directories.sort();
+        assert_eq!(
+            directories,
+            vec![("empty", ByteSize::ZERO), ("extra", ByteSize(120))]
+        );
+        assert_eq!(report.total_size, ByteSize(125));
+    }
+
+    #[test]
+    fn test_os_metadata_is_ignored() {
+        let root = restore_dir();
+        fs::write(root.path().join(".DS_Store"), b"meta").unwrap();
+        fs::write(root.path().join("docs/._will.pdf"), b"fork").unwrap();
+        fs::create_dir(root.path().join(".Spotlight-V100")).unwrap();
+        fs::write(root.path().join(".Spotlight-V100/store.db"), b"db").unwrap();
+        assert!(scan(&root).is_empty());
+
+        // Without the ignore list they count
+        let listed = listed(&["docs/will.pdf", "keys/recovery.txt"]);
+        let report = scan_unexpected(root.path(), &listed, &[]).unwrap();
+        assert_eq!(report.files.len(), 2);
+        assert_eq!(report.directories.len(), 1);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_symlink_outside_root_is_not_followed() {
+        let outside = TempDir::new().unwrap();
+        fs::write(outside.path().join("secret.txt"), vec![0u8; 4096]).unwrap();
+        let root = restore_dir();
+        std::os::unix::fs::symlink(outside.path(), root.path().join("docs/linked")).unwrap();
+
+        let report = scan(&root);
+        assert_eq!(report.files.len(), 1);
+        assert!(report.files[0].symlink);
+        assert!(report.files[0].path.ends_with("linked"));
+        assert!(report.files[0].size < ByteSize(4096));
+        assert!(report.directories.is_empty());
+
+        // A listed file reached through the link is refused
+        let entry = FileManifestEntry {
+            path: PathBuf::from("docs/linked/secret.txt"),
+            size: 4096,
+            modified: chrono::Utc::now(),
+            hash: calculate_file_hash(&outside.path().join("secret.txt")).unwrap(),
+            permissions: 0o644,
+        };
+        let canonical_root = root.path().canonicalize().unwrap();
+        let error = check_listed_file(root.path(), &canonical_root, &entry).unwrap_err();
+        assert!(error.contains("outside the restore directory"));
+    }
+
+    #[test]
+    fn test_listed_file_checks() {
+        let root = restore_dir();
+        let canonical_root = root.path().canonicalize().unwrap();
+        let entry = |path: &str, size: u64| FileManifestEntry {
+            path: PathBuf::from(path),
+            size,
+            modified: chrono::Utc::now(),
+            hash: calculate_file_hash(&root.path().join("docs/will.pdf")).unwrap(),
+            #[cfg(unix)]
+            permissions: 0o644,
+        };
+
+        assert!(
+            check_listed_file(root.path(), &canonical_root, &entry("docs/will.pdf", 4)).is_ok()
+        );
+        for (path, size) in [
+            ("docs/will.pdf", 5),
+            ("docs/gone.pdf", 4),
+            ("../will.pdf", 4),
+        ] {
+            assert!(check_listed_file(root.path(), &canonical_root, &entry(path, size)).is_err());
+        }
+    }
+
+    #[test]
+    fn test_report_is_capped() {
+        let root = restore_dir();
+        for i in 0..(MAX_REPORTED_UNEXPECTED + 3) {
+            fs::write(root.path().join(format!("extra-{i}.txt")), b"x").unwrap();
+        }
+
+        let report = scan(&root);
+        assert_eq!(report.files.len(), MAX_REPORTED_UNEXPECTED);
+        assert_eq!(report.omitted, 3);
+        assert_eq!(report.count(), MAX_REPORTED_UNEXPECTED + 3);
+        assert_eq!(
+            report.total_size,
+            ByteSize(MAX_REPORTED_UNEXPECTED as u64 + 3)
+        );
+    }
+}
@@ -0,0 +1,723 @@
+//! Streaming access to large archive manifests
+//!
+//! A manifest for a few hundred thousand files is tens of megabytes of JSON.
+//! Saved manifests open with an index (entry count, total size and the byte
+//! range of each shard of entries), so counts and sizes come from the first
+//! few hundred bytes, and entries are visited one at a time instead of being
+//! collected into a `Vec`. The file is still a plain JSON manifest: older
+//! readers ignore the index, and manifests saved before it existed stream
+//! the same way, only their summary needs a full scan.
+
+use super::super::{FileOpsError, Result};
+use super::types::{ArchiveManifest, FileManifestEntry, Manifest};
+use crate::constants::MANIFEST_SHARD_ENTRIES;
+use chrono::{DateTime, Utc};
+use serde::de::{self, DeserializeSeed, IgnoredAny, MapAccess, SeqAccess, Visitor};
+use serde::{Deserialize, Deserializer, Serialize};
+use sha2::{Digest, Sha256};
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+
+/// Opening bytes of a manifest saved with an index
+const INDEX_PREFIX: &[u8] = b"{\"index\":";
+/// Written between entries; one entry per line keeps the file diffable
+const ENTRY_SEPARATOR: &[u8] = b",\n";
+
+/// Entry count, total size and shard layout of a saved manifest
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ManifestIndex {
+    pub entry_count: usize,
+    pub total_size: u64,
+    pub shards: Vec<ManifestShard>,
+}
+
+/// Byte range of a run of consecutive entries in the manifest file
+///
+/// The range covers the entries and the separators between them, so the
+/// bytes wrapped in brackets form a JSON array.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ManifestShard {
+    pub offset: u64,
+    pub len: u64,
+    pub entries: usize,
+}
+
+/// Entry count and total size of a saved manifest
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ManifestSummary {
+    pub entry_count: usize,
+    pub total_size: u64,
+    /// Whether the figures came from the index rather than a full scan
+    pub indexed: bool,
+}
+
+/// Manifest fields gathered while its entries were streamed
+#[derive(Debug, Clone)]
+pub struct StreamedManifest {
+    pub version: String,
+    pub created: DateTime<Utc>,
+    pub archive: ArchiveManifest,
+    pub manifest_hash: String,
+    pub entry_count: usize,
+    pub total_size: u64,
+    /// Hash recomputed from the streamed content, as `calculate_manifest_hash` would
+    pub computed_hash: String,
+}
+
+impl StreamedManifest {
+    /// Same check as `Manifest::verify_integrity`
+    pub fn verify_integrity(&self) -> Result<()> {
+        if self.computed_hash != self.manifest_hash {
+            return Err(FileOpsError::ManifestVerificationFailed {
+                message: "Manifest hash verification failed".to_string(),
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Save a manifest with an index header
+///
+/// Entries are serialized once to measure them and once to write them, so
+/// the whole document is never built in memory.
+pub fn write_indexed_manifest(manifest: &Manifest, path: &Path) -> Result<ManifestIndex> {
+    let serialize_error = |e: serde_json::Error| FileOpsError::ManifestCreationFailed {
+        message: format!("Failed to serialize manifest: {e}"),
+    };
+
+    // Shard ranges relative to the first entry
+    let mut shards: Vec<ManifestShard> = Vec::new();
+    let mut position = 0u64;
+    for (i, entry) in manifest.files.iter().enumerate() {
+        if i > 0 {
+            position += ENTRY_SEPARATOR.len() as u64;
+        }
+        if i % MANIFEST_SHARD_ENTRIES == 0 {
+            shards.push(ManifestShard {
+                offset: position,
+                len: 0,
+                entries: 0,
+            });
+        }
+        let mut counter = ByteCounter::default();
+        serde_json::to_writer(&mut counter, entry).map_err(serialize_error)?;
+        position += counter.0;
+
+        let shard = shards.last_mut().expect("shard opened above");
+        shard.len = position - shard.offset;
+        shard.entries += 1;
+    }
+
+    // Offsets include the header, whose length depends on the offsets. It
+    // only grows as they do, so this settles within a few rounds.
+    let mut header_len = 0u64;
+    let (index, header) = loop {
+        let index = ManifestIndex {
+            entry_count: manifest.files.len(),
+            total_size: manifest.total_size(),
+            shards: shards
+                .iter()
+                .map(|shard| ManifestShard {
+                    offset: shard.offset + header_len,
+                    ..*shard
+                })
+                .collect(),
+        };
+        let header = header_bytes(manifest, &index).map_err(serialize_error)?;
+        if header.len() as u64 == header_len {
+            break (index, header);
+        }
+        header_len = header.len() as u64;
+    };
+
+    write_document(manifest, &header, path).map_err(|e| FileOpsError::IoError {
+        message: format!("Failed to write manifest file: {e}"),
+        source: e,
+    })?;
+
+    Ok(index)
+}
+
+/// Entry count and total size, from the index when the manifest has one
+pub fn read_manifest_summary(path: &Path) -> Result<ManifestSummary> {
+    let mut reader = BufReader::new(open_manifest(path)?);
+    let mut prefix = [0u8; INDEX_PREFIX.len()];
+    let indexed = reader.read_exact(&mut prefix).is_ok() && prefix == INDEX_PREFIX;
+
+    if indexed {
+        // Reads just the index; the deserializer stops at its closing brace
+        let index = ManifestIndex::deserialize(&mut serde_json::Deserializer::from_reader(reader))
+            .map_err(parse_error)?;
+        return Ok(ManifestSummary {
+            entry_count: index.entry_count,
+            total_size: index.total_size,
+            indexed: true,
+        });
+    }
+
+    let scanned = stream(path, false, |_| Ok(()))?;
+    Ok(ManifestSummary {
+        entry_count: scanned.entry_count,
+        total_size: scanned.total_size,
+        indexed: false,
+    })
+}
+
+/// Visit every entry of a saved manifest in order
+///
+/// Only one entry is held at a time. `on_entry` can stop the stream early by
+/// returning an error, which is passed through unchanged.
+pub fn stream_manifest_entries(
+    path: &Path,
+    on_entry: impl FnMut(FileManifestEntry) -> Result<()>,
+) -> Result<StreamedManifest> {
+    stream(path, true, on_entry)
+}
+
+/// Visit the entries of one shard of an indexed manifest
+pub fn read_manifest_shard(
+    path: &Path,
+    shard: &ManifestShard,
+    on_entry: impl FnMut(FileManifestEntry) -> Result<()>,
+) -> Result<()> {
+    let mut file = open_manifest(path)?;
+    file.seek(SeekFrom::Start(shard.offset)).map_err(|e| {
+        FileOpsError::ManifestVerificationFailed {
+            message: format!("Failed to read manifest file: {e}"),
+        }
+    })?;
+    let reader = BufReader::new((&b"["[..]).chain(file.take(shard.len)).chain(&b"]"[..]));
+
+    let mut state = StreamState::new(on_entry, false);
+    let result =
+        EntriesSeed(&mut state).deserialize(&mut serde_json::Deserializer::from_reader(reader));
+    if let Some(failure) = state.failure.take() {
+        return Err(failure);
+    }
+    result.map_err(parse_error)?;
+
+    if state.entry_count != shard.entries {
+        return Err(FileOpsError::ManifestVerificationFailed {
+            message: format!(
+                "Manifest shard holds {} entries, index expects {}",
+                state.entry_count, shard.entries
+            ),
+        });
+    }
+    Ok(())
+}
+
+fn stream<F>(path: &Path, hash: bool, on_entry: F) -> Result<StreamedManifest>
+where
+    F: FnMut(FileManifestEntry) -> Result<()>,
+{
+    let mut deserializer =
+        serde_json::Deserializer::from_reader(BufReader::new(open_manifest(path)?));
+    let mut state = StreamState::new(on_entry, hash);
+
+    let result = (&mut deserializer)
+        .deserialize_map(ManifestVisitor(&mut state))
+        .and_then(|()| deserializer.end());
+    if let Some(failure) = state.failure.take() {
+        return Err(failure);
+    }
+    result.map_err(parse_error)?;
+
+    state.finish()
+}
+
+fn open_manifest(path: &Path) -> Result<File> {
+    File::open(path).map_err(|e| FileOpsError::ManifestVerificationFailed {
+        message: format!("Failed to read manifest file: {e}"),
+    })
+}
+
+fn parse_error(e: serde_json::Error) -> FileOpsError {
+    FileOpsError::ManifestVerificationFailed {
+        message: format!("Failed to parse manifest JSON: {e}"),
+    }
+}
+
+/// `"version":…,"created":…,"archive":…` as the derived serializer writes them
+fn write_header_fields(
+    out: &mut Vec<u8>,
+    version: &str,
+    created: &DateTime<Utc>,
+    archive: &ArchiveManifest,
+) -> serde_json::Result<()> {
+    out.extend_from_slice(b"\"version\":");
+    serde_json::to_writer(&mut *out, version)?;
+    out.extend_from_slice(b",\"created\":");
+    serde_json::to_writer(&mut *out, created)?;
+    out.extend_from_slice(b",\"archive\":");
+    serde_json::to_writer(&mut *out, archive)
+}
+
+/// Everything before the first entry
+fn header_bytes(manifest: &Manifest, index: &ManifestIndex) -> serde_json::Result<Vec<u8>> {
+    let mut header = INDEX_PREFIX.to_vec();
+    serde_json::to_writer(&mut header, index)?;
+    header.push(b',');
+    write_header_fields(
+        &mut header,
+        &manifest.version,
+        &manifest.created,
+        &manifest.archive,
+    )?;
+    header.extend_from_slice(b",\"files\":[\n");
+    Ok(header)
+}
+
+fn write_document(manifest: &Manifest, header: &[u8], path: &Path) -> io::Result<()> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    writer.write_all(header)?;
+    for (i, entry) in manifest.files.iter().enumerate() {
+        if i > 0 {
+            writer.write_all(ENTRY_SEPARATOR)?;
+        }
+        serde_json::to_writer(&mut writer, entry)?;
+    }
+    writer.write_all(b"\n],\"manifest_hash\":")?;
+    serde_json::to_writer(&mut writer, &manifest.manifest_hash)?;
+    writer.write_all(b"}\n")?;
+    writer.flush()
+}
+
+#[derive(Default)]
+struct ByteCounter(u64);
+
+impl Write for ByteCounter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0 += buf.len() as u64;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Recomputes `calculate_manifest_hash` from streamed pieces
+///
+/// That hash covers the compact serialization with an empty `manifest_hash`.
+/// Entries that arrive before the header fields (valid JSON, though this app
+/// never writes it) are buffered until the header is known.
+#[derive(Default)]
+struct ContentHasher {
+    sha: Sha256,
+    started: bool,
+    pending: Vec<u8>,
+    scratch: Vec<u8>,
+    entries: usize,
+}
+
+impl ContentHasher {
+    fn start(
+        &mut self,
+        version: &str,
+        created: &DateTime<Utc>,
+        archive: &ArchiveManifest,
+    ) -> serde_json::Result<()> {
+        let mut prefix = b"{".to_vec();
+        write_header_fields(&mut prefix, version, created, archive)?;
+        prefix.extend_from_slice(b",\"files\":[");
+        self.sha.update(&prefix);
+        self.sha.update(std::mem::take(&mut self.pending));
+        self.started = true;
+        Ok(())
+    }
+
+    fn entry(&mut self, entry: &FileManifestEntry) -> serde_json::Result<()> {
+        self.scratch.clear();
+        if self.entries > 0 {
+            self.scratch.push(b',');
+        }
+        serde_json::to_writer(&mut self.scratch, entry)?;
+        if self.started {
+            self.sha.update(&self.scratch);
+        } else {
+            self.pending.extend_from_slice(&self.scratch);
+        }
+        self.entries += 1;
+        Ok(())
+    }
+}
+
+struct StreamState<F> {
+    on_entry: F,
+    hasher: Option<ContentHasher>,
+    version: Option<String>,
+    created: Option<DateTime<Utc>>,
+    archive: Option<ArchiveManifest>,
+    manifest_hash: Option<String>,
+    entry_count: usize,
+    total_size: u64,
+    /// Error from `on_entry` or the hasher, surfaced instead of the parse error
+    failure: Option<FileOpsError>,
+}
+
+impl<F> StreamState<F>
+where
+    F: FnMut(FileManifestEntry) -> Result<()>,
+{
+    fn new(on_entry: F, hash: bool) -> Self {
+        Self {
+            on_entry,
+            hasher: hash.then(ContentHasher::default),
+            version: None,
+            created: None,
+            archive: None,
+            manifest_hash: None,
+            entry_count: 0,
+            total_size: 0,
+            failure: None,
+        }
+    }
+
+    fn begin_entries(&mut self) -> Result<()> {
+        if let (Some(hasher), Some(version), Some(created), Some(archive)) = (
+            self.hasher.as_mut(),
+            &self.version,
+            &self.created,
+            &self.archive,
+        ) {
+            hasher
+                .start(version, created, archive)
+                .map_err(hash_error)?;
+        }
+        Ok(())
+    }
+
+    fn push(&mut self, entry: FileManifestEntry) -> Result<()> {
+        if let Some(hasher) = self.hasher.as_mut() {
+            hasher.entry(&entry).map_err(hash_error)?;
+        }
+        self.entry_count += 1;
+        self.total_size += entry.size;
+        (self.on_entry)(entry)
+    }
+
+    fn finish(self) -> Result<StreamedManifest> {
+        let missing = |field: &str| FileOpsError::ManifestVerificationFailed {
+            message: format!("Failed to parse manifest JSON: missing field `{field}`"),
+        };
+        let version = self.version.ok_or_else(|| missing("version"))?;
+        let created = self.created.ok_or_else(|| missing("created"))?;
+        let archive = self.archive.ok_or_else(|| missing("archive"))?;
+        let manifest_hash = self.manifest_hash.ok_or_else(|| missing("manifest_hash"))?;
+
+        let computed_hash = match self.hasher {
+            Some(mut hasher) => {
+                if !hasher.started {
+                    hasher
+                        .start(&version, &created, &archive)
+                        .map_err(hash_error)?;
+                }
+                hasher.sha.update(b"],\"manifest_hash\":\"\"}");
+                hex::encode(hasher.sha.finalize())
+            }
+            None => String::new(),
+        };
+
+        Ok(StreamedManifest {
+            version,
+            created,
+            archive,
+            manifest_hash,
+            entry_count: self.entry_count,
+            total_size: self.total_size,
+            computed_hash,
+        })
+    }
+}
+
+fn hash_error(e: serde_json::Error) -> FileOpsError {
+    FileOpsError::ManifestVerificationFailed {
+        message: format!("Failed to serialize manifest for hash calculation: {e}"),
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(field_identifier, rename_all = "snake_case")]
+enum Field {
+    Version,
+    Created,
+    Archive,
+    Files,
+    ManifestHash,
+    #[serde(other)]
+    Other,
+}
+
+/// Reads the top-level manifest object, handing `files` to `EntriesSeed`
+struct ManifestVisitor<'s, F>(&'s mut StreamState<F>);
+
+impl<'de, F> Visitor<'de> for ManifestVisitor<'_, F>
+where
+    F: FnMut(FileManifestEntry) -> Result<()>,
+{
+    type Value = ();
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("an archive manifest")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> std::result::Result<(), A::Error> {
+        let state = self.0;
+        let mut saw_files = false;
+
+        while let Some(field) = map.next_key()? {
+            match field {
+                Field::Version => state.version = Some(map.next_value()?),
+                Field::Created => state.created = Some(map.next_value()?),
+                Field::Archive => state.archive = Some(map.next_value()?),
+                Field::ManifestHash => state.manifest_hash = Some(map.next_value()?),
+                Field::Files => {
+                    if let Err(e) = state.begin_entries() {
+                        state.failure = Some(e);
+                        return Err(de::Error::custom("manifest hashing failed"));
+                    }
+                    map.next_value_seed(EntriesSeed(&mut *state))?;
+                    saw_files = true;
+                }
+                Field::Other => {
+                    map.next_value::<IgnoredAny>()?;
+                }
+            }
+        }
+
+        if !saw_files {
+            return Err(de::Error::missing_field("files"));
+        }
+        Ok(())
+    }
+}
+
+/// Reads an array of entries, passing each on as it is parsed
+struct EntriesSeed<'s, F>(&'s mut StreamState<F>);
+
+impl<'de, F> DeserializeSeed<'de> for EntriesSeed<'_, F>
+where
+    F: FnMut(FileManifestEntry) -> Result<()>,
+{
+    type Value = ();
+
+    fn deserialize<D: Deserializer<'de>>(
+        self,
+        deserializer: D,
+    ) -> std::result::Result<(), D::Error> {
+        deserializer.deserialize_seq(self)
+    }
+}
+
+impl<'de, F> Visitor<'de> for EntriesSeed<'_, F>
+where
+    F: FnMut(FileManifestEntry) -> Result<()>,
+{
+    type Value = ();
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a list of manifest entries")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> std::result::Result<(), A::Error> {
+        while let Some(entry) = seq.next_element()? {
+            if let Err(e) = self.0.push(entry) {
+                self.0.failure = Some(e);
+                return Err(de::Error::custom("manifest entry handler stopped"));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::verification::calculate_manifest_hash;
+    use super::*;
+    use std::path::PathBuf;
+    use tempfile::tempdir;
+
+    fn manifest(count: usize) -> Manifest {
+        let files: Vec<FileManifestEntry> = (0..count)
+            .map(|i| FileManifestEntry {
+                path: PathBuf::from(format!("mail/cur/{i:06}.eml")),
+                size: i as u64 + 1,
+                modified: DateTime::from_timestamp(1_700_000_000 + i as i64, 0).unwrap(),
+                hash: format!("{i:064x}"),
+                #[cfg(unix)]
+                permissions: 0o644,
+            })
+            .collect();
+        let mut manifest = Manifest {
+            version: "1.0".to_string(),
+            created: DateTime::from_timestamp(1_700_000_000, 0).unwrap(),
+            archive: ArchiveManifest {
+                archive_path: PathBuf::from("/tmp/mail.tar.gz"),
+                archive_size: 1024,
+                archive_hash: "ab".repeat(32),
+                total_uncompressed_size: files.iter().map(|f| f.size).sum(),
+                file_count: files.len(),
+                compression: "gzip".to_string(),
+                format: "tar".to_string(),
+            },
+            files,
+            manifest_hash: String::new(),
+        };
+        manifest.manifest_hash = calculate_manifest_hash(&manifest).unwrap();
+        manifest
+    }
+
+    fn paths(entries: &[FileManifestEntry]) -> Vec<PathBuf> {
+        entries.iter().map(|e| e.path.clone()).collect()
+    }
+
+    #[test]
+    fn test_indexed_manifest_still_loads_as_json() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("manifest.json");
+        let original = manifest(10);
+        original.save(&path).unwrap();
+
+        let loaded = Manifest::load(&path).unwrap();
+        assert_eq!(paths(&loaded.files), paths(&original.files));
+        assert_eq!(loaded.manifest_hash, original.manifest_hash);
+        loaded.verify_integrity().unwrap();
+    }
+
+    #[test]
+    fn test_summary_reads_index() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("manifest.json");
+        let original = manifest(MANIFEST_SHARD_ENTRIES * 2 + 5);
+        let index = write_indexed_manifest(&original, &path).unwrap();
+
+        assert_eq!(index.shards.len(), 3);
+        assert_eq!(index.shards[2].entries, 5);
+        assert_eq!(
+            read_manifest_summary(&path).unwrap(),
+            ManifestSummary {
+                entry_count: original.file_count(),
+                total_size: original.total_size(),
+                indexed: true,
+            }
+        );
+    }
+
+    #[test]
+    fn test_summary_scans_legacy_manifest() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("manifest.json");
+        let original = manifest(25);
+        fs_write_pretty(&original, &path);
+
+        assert_eq!(
+            read_manifest_summary(&path).unwrap(),
+            ManifestSummary {
+                entry_count: 25,
+                total_size: original.total_size(),
+                indexed: false,
+            }
+        );
+    }
+
+    #[test]
+    fn test_shards_cover_every_entry_in_order() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("manifest.json");
+        let original = manifest(MANIFEST_SHARD_ENTRIES + 3);
+        let index = write_indexed_manifest(&original, &path).unwrap();
+
+        let mut seen = Vec::new();
+        for shard in &index.shards {
+            read_manifest_shard(&path, shard, |entry| {
+                seen.push(entry.path);
+                Ok(())
+            })
+            .unwrap();
+        }
+        assert_eq!(seen, paths(&original.files));
+    }
+
+    #[test]
+    fn test_streamed_hash_matches_stored_hash() {
+        let dir = tempdir().unwrap();
+        for (name, original) in [("empty", manifest(0)), ("some", manifest(40))] {
+            let indexed = dir.path().join(format!("{name}.json"));
+            let legacy = dir.path().join(format!("{name}-legacy.json"));
+            original.save(&indexed).unwrap();
+            fs_write_pretty(&original, &legacy);
+
+            for path in [indexed, legacy] {
+                let mut seen = Vec::new();
+                let streamed = stream_manifest_entries(&path, |entry| {
+                    seen.push(entry.path);
+                    Ok(())
+                })
+                .unwrap();
+
+                assert_eq!(streamed.computed_hash, original.manifest_hash);
+                streamed.verify_integrity().unwrap();
+                assert_eq!(seen, paths(&original.files));
+            }
+        }
+    }
+
+    #[test]
+    fn test_streamed_hash_detects_tampering() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("manifest.json");
+        let mut tampered = manifest(3);
+        tampered.files[1].size += 1;
+        tampered.save(&path).unwrap();
+
+        let streamed = stream_manifest_entries(&path, |_| Ok(())).unwrap();
+        assert!(streamed.verify_integrity().is_err());
+    }
+
+    #[test]
+    fn test_fields_after_entries_still_hash() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("manifest.json");
+        let original = manifest(4);
+        let reordered = serde_json::json!({
+            "files": original.files,
+            "manifest_hash": original.manifest_hash,
+            "archive": original.archive,
+            "created": original.created,
+            "version": original.version,
+        });
+        std::fs::write(&path, reordered.to_string()).unwrap();
+
+        let streamed = stream_manifest_entries(&path, |_| Ok(())).unwrap();
+        streamed.verify_integrity().unwrap();
+        assert_eq!(streamed.entry_count, 4);
+    }
+
+    #[test]
+    fn test_handler_error_stops_stream() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("manifest.json");
+        manifest(10).save(&path).unwrap();
+
+        let mut visited = 0;
+        let result = stream_manifest_entries(&path, |_| {
+            visited += 1;
+            if visited == 3 {
+                return Err(FileOpsError::ManifestVerificationFailed {
+                    message: "stop here".to_string(),
+                });
+            }
+            Ok(())
+        });
+
+        assert_eq!(visited, 3);
+        assert!(result.unwrap_err().to_string().contains("stop here"));
+    }
+
+    fn fs_write_pretty(manifest: &Manifest, path: &Path) {
+        std::fs::write(path, serde_json::to_string_pretty(manifest).unwrap()).unwrap();
+    }
+}
@@ -4,8 +4,11 @@
 //! and calculating hashes for integrity checking.
 
 use super::super::{FileInfo, FileOpsConfig, FileOpsError, Result};
-use super::types::Manifest;
+use super::streaming::stream_manifest_entries;
+use super::types::{FileManifestEntry, Manifest};
 use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
 use tracing::{info, warn};
 
 /// Verify manifest against extracted files
@@ -86,6 +89,158 @@ pub fn verify_manifest(
     Ok(())
 }
 
+/// Check that recorded directories exist under `root`
+///
+/// Directories have no content, so only their presence is verified.
+pub fn verify_directories(root: &Path, directories: &[PathBuf]) -> Result<()> {
+    let missing: Vec<String> = directories
+        .iter()
+        .filter(|directory| !root.join(directory).is_dir())
+        .map(|directory| directory.display().to_string())
+        .collect();
+
+    if !missing.is_empty() {
+        return Err(FileOpsError::ManifestVerificationFailed {
+            message: format!("Directories missing after restore: {}", missing.join(", ")),
+        });
+    }
+
+    info!("Verified {} directories", directories.len());
+    Ok(())
+}
+
+/// Verify a saved manifest against extracted files without loading it
+///
+/// Same outcome and errors as `Manifest::load` followed by `verify_manifest`,
+/// but entries are checked as they are read, so memory follows the number of
+/// extracted files rather than the size of the manifest.
+pub fn verify_manifest_file(
+    manifest_path: &Path,
+    extracted_files: &[FileInfo],
+    _config: &FileOpsConfig,
+) -> Result<()> {
+    info!(
+        "Verifying manifest {} against {} extracted files",
+        manifest_path.display(),
+        extracted_files.len()
+    );
+
+    let mut checks = ExtractedFileChecks::new(extracted_files);
+    let streamed = stream_manifest_entries(manifest_path, |entry| {
+        checks.check(&entry);
+        Ok(())
+    })?;
+
+    streamed.verify_integrity()?;
+    checks.finish(streamed.entry_count)?;
+
+    info!("Manifest verification completed successfully");
+    Ok(())
+}
+
+/// Extracted files matched against manifest entries as they stream past
+struct ExtractedFileChecks<'a> {
+    files: &'a [FileInfo],
+    /// Index of the first extracted file with each path
+    by_path: HashMap<&'a Path, usize>,
+    matched: Vec<bool>,
+    /// Mismatch message per extracted file index; only failures are stored
+    mismatches: BTreeMap<usize, String>,
+}
+
+impl<'a> ExtractedFileChecks<'a> {
+    fn new(files: &'a [FileInfo]) -> Self {
+        let mut by_path = HashMap::with_capacity(files.len());
+        for (i, file) in files.iter().enumerate() {
+            by_path.entry(file.path.as_path()).or_insert(i);
+        }
+
+        Self {
+            files,
+            by_path,
+            matched: vec![false; files.len()],
+            mismatches: BTreeMap::new(),
+        }
+    }
+
+    /// Compare an entry with its extracted file; like `get_file_entry`, only
+    /// the first entry for a path counts
+    fn check(&mut self, entry: &FileManifestEntry) {
+        let Some(&i) = self.by_path.get(entry.path.as_path()) else {
+            return;
+        };
+        if std::mem::replace(&mut self.matched[i], true) {
+            return;
+        }
+
+        let extracted_file = &self.files[i];
+        if entry.size != extracted_file.size {
+            self.mismatches.insert(
+                i,
+                format!(
+                    "File size mismatch for {}: manifest has {}, extracted has {}",
+                    extracted_file.path.display(),
+                    entry.size,
+                    extracted_file.size
+                ),
+            );
+        } else if entry.hash != extracted_file.hash {
+            self.mismatches.insert(
+                i,
+                format!(
+                    "File hash mismatch for {}: manifest has {}, extracted has {}",
+                    extracted_file.path.display(),
+                    entry.hash,
+                    extracted_file.hash
+                ),
+            );
+        } else {
+            #[cfg(unix)]
+            {
+                if entry.permissions != extracted_file.permissions {
+                    warn!(
+                        "File permissions mismatch for {}: manifest has {:o}, extracted has {:o}",
+                        extracted_file.path.display(),
+                        entry.permissions,
+                        extracted_file.permissions
+                    );
+                }
+            }
+        }
+    }
+
+    /// Report the failure `verify_manifest` would have hit first
+    fn finish(self, entry_count: usize) -> Result<()> {
+        if entry_count != self.files.len() {
+            return Err(FileOpsError::ManifestVerificationFailed {
+                message: format!(
+                    "File count mismatch: manifest has {}, extracted has {}",
+                    entry_count,
+                    self.files.len()
+                ),
+            });
+        }
+
+        for extracted_file in self.files {
+            let i = self.by_path[extracted_file.path.as_path()];
+            if !self.matched[i] {
+                return Err(FileOpsError::ManifestVerificationFailed {
+                    message: format!(
+                        "File not found in manifest: {}",
+                        extracted_file.path.display()
+                    ),
+                });
+            }
+            if let Some(message) = self.mismatches.get(&i) {
+                return Err(FileOpsError::ManifestVerificationFailed {
+                    message: message.clone(),
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
 /// Calculate SHA-256 hash of manifest content
 pub fn calculate_manifest_hash(manifest: &Manifest) -> Result<String> {
     // Create a copy without the hash field for calculation
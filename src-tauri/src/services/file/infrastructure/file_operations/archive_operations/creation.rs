@@ -2,19 +2,23 @@
 //!
 //! This module handles the creation of TAR.GZ archives from file selections.
 
+use super::super::raw_path::archive_entry_name;
 use super::super::staging::StagingArea;
 use super::super::utils::calculate_file_hash;
-use super::super::validation::validate_archive_path;
+use super::super::validation::safety_limits::{ArchiveSafetyLimits, ByteCounter, SafetyTally};
+use super::super::validation::{audit_portability, validate_archive_path};
 use super::super::{
     ArchiveInfo, ArchiveOperation, FileOpsConfig, FileOpsError, FileSelection, ProgressCallback,
     Result,
 };
+use crate::constants::EXPANSION_RATIO_MIN_ENTRY_SIZE;
 use flate2::Compression;
 use flate2::write::GzEncoder;
-use std::fs::File;
-use std::path::Path;
-use tar::Builder;
-use tracing::info;
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use tar::{Builder, EntryType, Header};
+use tracing::{info, warn};
 
 /// Create a TAR.GZ archive from file selection
 pub fn create_archive(
@@ -171,11 +175,25 @@ pub fn create_tar_gz(
     output_path: &Path,
     config: &FileOpsConfig,
 ) -> Result<ArchiveInfo> {
+    create_tar_gz_with_progress(staging, output_path, config, &mut |_| {})
+}
+
+/// Create TAR.GZ archive with progress reporting
+pub(super) fn create_tar_gz_with_progress(
+    staging: &StagingArea,
+    output_path: &Path,
+    config: &FileOpsConfig,
+    progress_callback: &mut dyn FnMut(u64),
+) -> Result<ArchiveInfo> {
+    // Refuse an archive past the safety limits before writing any of it
+    let tally = check_staged_entries(staging, config.safety_limits)?;
+
     // Create output file
     let output_file = File::create(output_path).map_err(|e| FileOpsError::IoError {
         message: format!("Failed to create archive file: {e}"),
         source: e,
     })?;
+    let (output_file, compressed_written) = ByteCounter::new(output_file);
 
     // Create GZIP encoder
     let gz_encoder = GzEncoder::new(output_file, Compression::new(config.compression_level));
@@ -183,7 +201,9 @@ pub fn create_tar_gz(
     // Create TAR builder
     let mut tar_builder = Builder::new(gz_encoder);
 
-    // Add files to archive
+    warn_unportable_paths(staging);
+
+    // Add files to archive with progress
     for file_info in staging.staged_files() {
         let mut file = File::open(&file_info.path).map_err(|_e| FileOpsError::FileNotFound {
             path: file_info.path.clone(),
@@ -195,13 +215,36 @@ pub fn create_tar_gz(
             }
         })?;
 
+        // Large entries are flushed on both sides so the bytes written
+        // between are exactly their compressed size
+        let measured = file_info.size >= EXPANSION_RATIO_MIN_ENTRY_SIZE;
+        if measured {
+            flush_compressed(&mut tar_builder)?;
+        }
+        let compressed_before = compressed_written.get();
+
         tar_builder
-            .append_file(relative_path, &mut file)
+            .append_file(archive_entry_name(relative_path), &mut file)
             .map_err(|e| FileOpsError::ArchiveCreationFailed {
                 message: format!("Failed to add file to archive: {e}"),
             })?;
+
+        if measured {
+            flush_compressed(&mut tar_builder)?;
+            let compressed = compressed_written.get() - compressed_before;
+            if let Err(e) = tally.check_expansion(file_info.size, compressed) {
+                drop(tar_builder);
+                let _ = fs::remove_file(output_path);
+                return Err(e);
+            }
+        }
+
+        // Report progress
+        progress_callback(file_info.size);
     }
 
+    append_directories(&mut tar_builder, staging)?;
+
     // Finish archive
     let gz_encoder = tar_builder
         .into_inner()
@@ -213,7 +256,8 @@ pub fn create_tar_gz(
         .finish()
         .map_err(|e| FileOpsError::ArchiveCreationFailed {
             message: format!("Failed to finish GZIP compression: {e}"),
-        })?;
+        })?
+        .into_inner();
 
     // Get archive size
     let compressed_size = output_file
@@ -235,76 +279,82 @@ pub fn create_tar_gz(
     })
 }
 
-/// Create TAR.GZ archive with progress reporting
-pub(super) fn create_tar_gz_with_progress(
-    staging: &StagingArea,
-    output_path: &Path,
-    config: &FileOpsConfig,
-    progress_callback: &mut dyn FnMut(u64),
-) -> Result<ArchiveInfo> {
-    // Create output file
-    let output_file = File::create(output_path).map_err(|e| FileOpsError::IoError {
-        message: format!("Failed to create archive file: {e}"),
-        source: e,
-    })?;
-
-    // Create GZIP encoder
-    let gz_encoder = GzEncoder::new(output_file, Compression::new(config.compression_level));
-
-    // Create TAR builder
-    let mut tar_builder = Builder::new(gz_encoder);
+/// Check the staged files and directories against the safety limits
+fn check_staged_entries(staging: &StagingArea, limits: ArchiveSafetyLimits) -> Result<SafetyTally> {
+    let mut tally = SafetyTally::new(limits);
+    let entries = staging
+        .staged_files()
+        .iter()
+        .map(|file| (&file.path, file.size))
+        .chain(
+            staging
+                .staged_directories()
+                .iter()
+                .map(|directory| (&directory.path, 0)),
+        );
+    for (path, size) in entries {
+        let relative_path = path.strip_prefix(staging.path()).unwrap_or(path);
+        tally.add_entry(relative_path, size)?;
+    }
+    Ok(tally)
+}
 
-    // Add files to archive with progress
-    for file_info in staging.staged_files() {
-        let mut file = File::open(&file_info.path).map_err(|_e| FileOpsError::FileNotFound {
-            path: file_info.path.clone(),
-        })?;
+/// Push everything compressed so far through to the output file
+fn flush_compressed<W: Write>(tar_builder: &mut Builder<W>) -> Result<()> {
+    tar_builder
+        .get_mut()
+        .flush()
+        .map_err(|e| FileOpsError::ArchiveCreationFailed {
+            message: format!("Failed to flush archive: {e}"),
+        })
+}
 
-        let relative_path = file_info.path.strip_prefix(staging.path()).map_err(|e| {
+/// Add a directory entry for every staged directory
+///
+/// Written after the files and children before parents, so extraction can
+/// apply each directory's mode and timestamp once nothing more is written
+/// into it.
+fn append_directories<W: Write>(tar_builder: &mut Builder<W>, staging: &StagingArea) -> Result<()> {
+    for directory in staging.staged_directories().iter().rev() {
+        let relative_path = directory.path.strip_prefix(staging.path()).map_err(|e| {
             FileOpsError::CrossPlatformPathError {
                 message: format!("Failed to get relative path: {e}"),
             }
         })?;
 
+        let mut header = Header::new_gnu();
+        header.set_entry_type(EntryType::Directory);
+        header.set_size(0);
+        header.set_mtime(directory.modified.timestamp().max(0) as u64);
+        #[cfg(unix)]
+        header.set_mode(directory.permissions & 0o7777);
+        #[cfg(not(unix))]
+        header.set_mode(0o755);
+        header.set_cksum();
+
         tar_builder
-            .append_file(relative_path, &mut file)
+            .append_data(&mut header, archive_entry_name(relative_path), io::empty())
             .map_err(|e| FileOpsError::ArchiveCreationFailed {
-                message: format!("Failed to add file to archive: {e}"),
+                message: format!("Failed to add directory to archive: {e}"),
             })?;
-
-        // Report progress
-        progress_callback(file_info.size);
     }
 
-    // Finish archive
-    let gz_encoder = tar_builder
-        .into_inner()
-        .map_err(|e| FileOpsError::ArchiveCreationFailed {
-            message: format!("Failed to finalize archive: {e}"),
-        })?;
-
-    let output_file = gz_encoder
-        .finish()
-        .map_err(|e| FileOpsError::ArchiveCreationFailed {
-            message: format!("Failed to finish GZIP compression: {e}"),
-        })?;
-
-    // Get archive size
-    let compressed_size = output_file
-        .metadata()
-        .map_err(|e| FileOpsError::IoError {
-            message: format!("Failed to get archive metadata: {e}"),
-            source: e,
-        })?
-        .len();
-
-    // Calculate archive hash
-    let archive_hash = calculate_file_hash(output_path)?;
+    Ok(())
+}
 
-    Ok(ArchiveInfo {
-        compressed_size,
-        uncompressed_size: staging.total_size(),
-        file_count: staging.file_count(),
-        archive_hash,
-    })
+/// Warn about staged files whose paths would be un-restorable on other platforms
+fn warn_unportable_paths(staging: &StagingArea) {
+    let relative_paths: Vec<PathBuf> = staging
+        .staged_files()
+        .iter()
+        .filter_map(|f| f.path.strip_prefix(staging.path()).ok())
+        .map(Path::to_path_buf)
+        .collect();
+
+    for violation in audit_portability(&relative_paths) {
+        warn!(
+            "File may not be restorable on {}: {}",
+            violation.platform, violation
+        );
+    }
 }
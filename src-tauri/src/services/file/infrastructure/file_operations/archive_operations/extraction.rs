@@ -1,18 +1,74 @@
 //! Archive extraction functionality
 //!
 //! This module handles the extraction of TAR.GZ archives.
+//!
+//! Before anything is written, an archive that doesn't record the safety
+//! limits it was created under is pre-scanned against them (see
+//! `validation::safety_limits`), and every file entry is audited against the
+//! platform's path length limits so extraction never fails partway through
+//! with a raw OS error. Offending entries are handled according to
+//! `FileOpsConfig::path_limit_strategy`.
+//!
+//! `FileOpsConfig::restore_filter` limits extraction to selected paths or
+//! content types. Vault manifests and key files are always written, since
+//! decryption relies on them.
+//!
+//! With `FileOpsConfig::resume_journal` set, files an interrupted run already
+//! wrote are kept when their hash still matches, and each newly written file
+//! is recorded so a later run can do the same.
+//!
+//! Directory entries are recreated even when empty. Their mode and timestamp
+//! are applied after every file is written, so a read-only directory doesn't
+//! block its own contents.
 
+use super::super::content_type::classify_content;
 use super::super::utils::calculate_file_hash;
-use super::super::validation::contains_traversal_attempt;
+use super::super::validation::path_limits::{
+    PathLimitStrategy, PathLimits, audit_entry_paths, file_name_budget, native_relative_path,
+    short_hash, shorten_name, shorten_parent_path,
+};
+use super::super::validation::{contains_traversal_attempt, prescan_archive};
 use super::super::{FileInfo, FileOpsConfig, FileOpsError, Result};
+use crate::constants::{CONTENT_SNIFF_LIMIT, SHORTENED_NAME_HASH_LEN};
 use flate2::read::GzDecoder;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
 use std::fs::{self, File};
-use std::io;
+use std::io::{self, Read, Write};
 #[cfg(unix)]
 use std::os::unix::fs::PermissionsExt;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use tar::Archive;
-use tracing::info;
+use tracing::{debug, info, warn};
+
+/// Original archive path and where it was written after shortening
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PathMapping {
+    /// Entry path as stored in the archive
+    pub original_path: PathBuf,
+    /// Path the entry was written to
+    pub extracted_path: PathBuf,
+}
+
+/// Extracted files plus any entries renamed to satisfy path limits
+#[derive(Debug, Clone, Default)]
+pub struct ExtractionResult {
+    pub files: Vec<FileInfo>,
+    pub renamed_paths: Vec<PathMapping>,
+    /// File entries left out by the restore filter
+    pub skipped_by_filter: usize,
+    /// Directories recreated from directory entries
+    pub directories: Vec<PathBuf>,
+    /// Files kept from an interrupted earlier extraction
+    pub resumed: usize,
+}
+
+/// A recreated directory whose mode and timestamp are applied last
+struct RestoredDirectory {
+    path: PathBuf,
+    mode: Option<u32>,
+    mtime: Option<u64>,
+}
 
 /// Extract a TAR.GZ archive
 pub fn extract_archive(
@@ -20,6 +76,15 @@ pub fn extract_archive(
     output_dir: &Path,
     config: &FileOpsConfig,
 ) -> Result<Vec<FileInfo>> {
+    Ok(extract_archive_with_report(archive_path, output_dir, config)?.files)
+}
+
+/// Extract a TAR.GZ archive, reporting entries renamed to satisfy path limits
+pub fn extract_archive_with_report(
+    archive_path: &Path,
+    output_dir: &Path,
+    config: &FileOpsConfig,
+) -> Result<ExtractionResult> {
     debug_assert!(
         !archive_path.as_os_str().is_empty(),
         "Archive path cannot be empty"
@@ -42,12 +107,31 @@ pub fn extract_archive(
         });
     }
 
+    // Archives that don't record being created within the safety limits are
+    // scanned for them before anything is written
+    match config.recorded_safety_limits {
+        Some(recorded) if recorded.satisfies(&config.safety_limits) => {
+            debug!("Archive records its safety limits; skipping pre-scan");
+        }
+        _ => prescan_archive(archive_path, config.safety_limits)?,
+    }
+
     // Create output directory if it doesn't exist
     fs::create_dir_all(output_dir).map_err(|e| FileOpsError::IoError {
         message: format!("Failed to create output directory: {e}"),
         source: e,
     })?;
 
+    // Audit entry paths against platform limits before writing anything
+    let (write_root, limits) = resolve_write_root(output_dir, config.path_limit_strategy)?;
+    let entry_paths = collect_file_entry_paths(archive_path)?;
+    let shortened_entries = plan_path_limit_remediation(
+        &limits,
+        &write_root,
+        &entry_paths,
+        config.path_limit_strategy,
+    )?;
+
     // Open and validate archive
     let archive_file =
         File::open(archive_path).map_err(|e| FileOpsError::ArchiveExtractionFailed {
@@ -62,6 +146,11 @@ pub fn extract_archive(
     archive.set_preserve_permissions(config.preserve_permissions);
 
     let mut extracted_files = Vec::new();
+    let mut renamed_paths = Vec::new();
+    let mut assigned_paths = HashSet::new();
+    let mut skipped_by_filter = 0usize;
+    let mut restored_directories = Vec::new();
+    let mut resumed = 0usize;
 
     // Extract files
     for entry_result in archive
@@ -89,13 +178,75 @@ pub fn extract_archive(
             });
         }
 
-        let output_path = output_dir.join(&path);
+        if entry.header().entry_type().is_dir() {
+            if let Some(directory) = restore_directory(&entry, &path, &write_root, &limits, config)?
+            {
+                restored_directories.push(directory);
+            }
+            continue;
+        }
+
+        // Other than directories, only files are written; parent directories
+        // are created on demand
+        if !entry.header().entry_type().is_file() {
+            continue;
+        }
+
+        // Leading bytes are read up front when the filter selects by type
+        let mut head = Vec::new();
+        let filter = &config.restore_filter;
+        if !filter.is_empty() && !is_internal_entry(&path) {
+            if filter.filters_by_type() {
+                (&mut entry)
+                    .take(CONTENT_SNIFF_LIMIT as u64)
+                    .read_to_end(&mut head)
+                    .map_err(|e| FileOpsError::IoError {
+                        message: format!("Failed to read archive entry: {e}"),
+                        source: e,
+                    })?;
+            }
+            let content_type = classify_content(&path, &head).map(|info| info.content_type);
+            if !filter.includes(&path, content_type.as_deref()) {
+                skipped_by_filter += 1;
+                continue;
+            }
+        }
+
+        // Files an interrupted run finished are kept when still intact
+        if let Some(journal) = &config.resume_journal
+            && let Some((output_path, hash)) = journal.verified(&path, &write_root)
+        {
+            if shortened_entries.contains(&path) {
+                renamed_paths.push(PathMapping {
+                    original_path: path.clone(),
+                    extracted_path: output_path.clone(),
+                });
+            }
+            assigned_paths.insert(output_path.clone());
+            extracted_files.push(extracted_file_info(output_path, hash)?);
+            resumed += 1;
+            continue;
+        }
+
+        // Entries over the limits are written to a short temporary name inside
+        // their shortened parent, then renamed once the content hash is known
+        let shorten = shortened_entries.contains(&path);
+        let relative_path = if shorten {
+            let parent = shorten_parent_path(&limits, &path).unwrap_or_default();
+            parent.join(format!(
+                ".~{}",
+                short_hash(path.to_string_lossy().as_bytes())
+            ))
+        } else {
+            native_relative_path(&path)
+        };
+        let output_path = write_root.join(&relative_path);
 
         // Ensure the resolved path is still within the output directory
         // This catches symbolic links and other path resolution attacks
-        let canonical_output_dir = output_dir
+        let canonical_output_dir = write_root
             .canonicalize()
-            .unwrap_or_else(|_| output_dir.to_path_buf());
+            .unwrap_or_else(|_| write_root.clone());
 
         // For the output path, we need to check the parent directory since the file doesn't exist yet
         let output_parent =
@@ -131,44 +282,679 @@ pub fn extract_archive(
         }
 
         // Extract file
-        if entry.header().entry_type().is_file() {
-            let mut output_file =
-                File::create(&output_path).map_err(|e| FileOpsError::IoError {
-                    message: format!("Failed to create output file: {e}"),
-                    source: e,
-                })?;
+        let mut output_file = File::create(&output_path).map_err(|e| FileOpsError::IoError {
+            message: format!("Failed to create output file: {e}"),
+            source: e,
+        })?;
 
-            io::copy(&mut entry, &mut output_file).map_err(|e| FileOpsError::IoError {
+        output_file
+            .write_all(&head)
+            .and_then(|_| match &config.io_pacer {
+                Some(pacer) => pacer.copy(&mut entry, &mut output_file),
+                None => io::copy(&mut entry, &mut output_file),
+            })
+            .map_err(|e| FileOpsError::IoError {
                 message: format!("Failed to extract file: {e}"),
                 source: e,
             })?;
+        drop(output_file);
 
-            // Get file metadata
-            let metadata = fs::metadata(&output_path).map_err(|_e| FileOpsError::FileNotFound {
-                path: output_path.clone(),
-            })?;
+        let hash = calculate_file_hash(&output_path)?;
 
-            let file_info = FileInfo {
-                path: output_path.clone(),
-                size: metadata.len(),
-                modified: chrono::DateTime::from(
-                    metadata
-                        .modified()
-                        .unwrap_or_else(|_| std::time::SystemTime::now()),
-                ),
-                hash: calculate_file_hash(&output_path)?,
-                #[cfg(unix)]
-                permissions: metadata.permissions().mode(),
-            };
+        let output_path = if shorten {
+            let final_path = finalize_shortened_entry(
+                &limits,
+                &write_root,
+                &path,
+                &output_path,
+                &hash,
+                &assigned_paths,
+            )?;
+            warn!(
+                "Shortened archive entry to fit path limits: {} -> {}",
+                path.display(),
+                final_path.display()
+            );
+            renamed_paths.push(PathMapping {
+                original_path: path.clone(),
+                extracted_path: final_path.clone(),
+            });
+            final_path
+        } else {
+            output_path
+        };
+        assigned_paths.insert(output_path.clone());
 
-            extracted_files.push(file_info);
-            info!("Extracted file: {}", path.display());
+        if let Some(journal) = &config.resume_journal {
+            let relative = output_path
+                .strip_prefix(&write_root)
+                .unwrap_or(&output_path);
+            journal.record(&path, relative, &hash)?;
         }
+
+        extracted_files.push(extracted_file_info(output_path, hash)?);
+        info!("Extracted file: {}", path.display());
+    }
+
+    // Deepest first, so a parent's timestamp isn't bumped by a child
+    restored_directories.sort_by_key(|d| std::cmp::Reverse(d.path.components().count()));
+    for directory in &restored_directories {
+        apply_directory_attributes(directory, config.preserve_permissions);
     }
 
     info!(
-        "Archive extraction completed: {} files",
-        extracted_files.len()
+        "Archive extraction completed: {} files, {} directories ({} renamed, {} filtered out, {} resumed)",
+        extracted_files.len(),
+        restored_directories.len(),
+        renamed_paths.len(),
+        skipped_by_filter,
+        resumed
     );
-    Ok(extracted_files)
+    Ok(ExtractionResult {
+        files: extracted_files,
+        renamed_paths,
+        skipped_by_filter,
+        directories: restored_directories.into_iter().map(|d| d.path).collect(),
+        resumed,
+    })
+}
+
+/// Describe a file written (or kept) by extraction
+fn extracted_file_info(path: PathBuf, hash: String) -> Result<FileInfo> {
+    let metadata =
+        fs::metadata(&path).map_err(|_e| FileOpsError::FileNotFound { path: path.clone() })?;
+
+    Ok(FileInfo {
+        size: metadata.len(),
+        modified: chrono::DateTime::from(
+            metadata
+                .modified()
+                .unwrap_or_else(|_| std::time::SystemTime::now()),
+        ),
+        hash,
+        #[cfg(unix)]
+        permissions: metadata.permissions().mode(),
+        path,
+    })
+}
+
+/// Recreate the directory of a directory entry
+///
+/// Returns `None` when the restore filter leaves it out, or when it is over
+/// the path limits (nothing is written into it, so there is no file to
+/// shorten). Fails if a file already exists at the directory's path.
+fn restore_directory<R: Read>(
+    entry: &tar::Entry<'_, R>,
+    path: &Path,
+    write_root: &Path,
+    limits: &PathLimits,
+    config: &FileOpsConfig,
+) -> Result<Option<RestoredDirectory>> {
+    if !config.restore_filter.includes(path, None) {
+        return Ok(None);
+    }
+
+    if !audit_entry_paths(limits, write_root, &[path.to_path_buf()]).is_empty() {
+        warn!(
+            "Skipping directory over the path limits: {}",
+            path.display()
+        );
+        return Ok(None);
+    }
+
+    let output_path = write_root.join(native_relative_path(path));
+    if output_path.exists() && !output_path.is_dir() {
+        return Err(FileOpsError::PathValidationFailed {
+            path: output_path,
+            reason: "A file already exists where the archive has a directory".to_string(),
+        });
+    }
+
+    fs::create_dir_all(&output_path).map_err(|e| FileOpsError::IoError {
+        message: format!("Failed to create directory: {e}"),
+        source: e,
+    })?;
+
+    // Same containment check as for files: symlinks must not lead outside
+    let canonical_output_dir = write_root
+        .canonicalize()
+        .unwrap_or_else(|_| write_root.to_path_buf());
+    let canonical_path =
+        output_path
+            .canonicalize()
+            .map_err(|e| FileOpsError::PathValidationFailed {
+                path: output_path.clone(),
+                reason: format!("Failed to resolve directory: {e}"),
+            })?;
+    if !canonical_path.starts_with(&canonical_output_dir) {
+        return Err(FileOpsError::PathValidationFailed {
+            path: output_path,
+            reason: "Archive entry would extract outside of output directory".to_string(),
+        });
+    }
+
+    info!("Restored directory: {}", path.display());
+    Ok(Some(RestoredDirectory {
+        path: output_path,
+        mode: entry.header().mode().ok(),
+        mtime: entry.header().mtime().ok(),
+    }))
+}
+
+/// Apply a restored directory's recorded mode and timestamp; failures only warn
+fn apply_directory_attributes(directory: &RestoredDirectory, preserve_permissions: bool) {
+    #[cfg(unix)]
+    {
+        if let Some(mtime) = directory.mtime {
+            let modified = std::time::UNIX_EPOCH + std::time::Duration::from_secs(mtime);
+            if let Err(e) = File::open(&directory.path).and_then(|dir| dir.set_modified(modified)) {
+                warn!(
+                    "Failed to restore directory timestamp for {}: {e}",
+                    directory.path.display()
+                );
+            }
+        }
+
+        if preserve_permissions
+            && let Some(mode) = directory.mode
+            && let Err(e) = fs::set_permissions(&directory.path, fs::Permissions::from_mode(mode))
+        {
+            warn!(
+                "Failed to restore directory permissions for {}: {e}",
+                directory.path.display()
+            );
+        }
+    }
+
+    #[cfg(not(unix))]
+    let _ = (directory.mode, directory.mtime, preserve_permissions);
+}
+
+/// Vault manifests and key files, which every restore needs
+pub(super) fn is_internal_entry(path: &Path) -> bool {
+    path.file_name()
+        .map(|name| name.to_string_lossy())
+        .is_some_and(|name| name.ends_with(".manifest") || name.ends_with(".agekey.enc"))
+}
+
+/// Choose the directory entries are written under and the limits that apply
+///
+/// `UseExtendedPaths` writes through the canonical `\\?\` form of the output
+/// directory on Windows; elsewhere it falls back to `Fail`.
+fn resolve_write_root(
+    output_dir: &Path,
+    strategy: PathLimitStrategy,
+) -> Result<(PathBuf, PathLimits)> {
+    if strategy == PathLimitStrategy::UseExtendedPaths {
+        if cfg!(windows) {
+            // canonicalize returns verbatim (\\?\-prefixed) paths on Windows
+            let extended =
+                output_dir
+                    .canonicalize()
+                    .map_err(|e| FileOpsError::PathValidationFailed {
+                        path: output_dir.to_path_buf(),
+                        reason: format!("Failed to resolve extended-length path: {e}"),
+                    })?;
+            return Ok((extended, PathLimits::windows_extended()));
+        }
+
+        warn!("Extended-length paths are only available on Windows; enforcing path limits");
+    }
+
+    Ok((output_dir.to_path_buf(), PathLimits::current()))
+}
+
+/// Read the paths of all file entries without extracting them
+fn collect_file_entry_paths(archive_path: &Path) -> Result<Vec<PathBuf>> {
+    let archive_file =
+        File::open(archive_path).map_err(|e| FileOpsError::ArchiveExtractionFailed {
+            message: format!("Failed to open archive: {e}"),
+        })?;
+    let mut archive = Archive::new(GzDecoder::new(archive_file));
+
+    let mut paths = Vec::new();
+    for entry_result in archive
+        .entries()
+        .map_err(|e| FileOpsError::ArchiveExtractionFailed {
+            message: format!("Failed to read archive entries: {e}"),
+        })?
+    {
+        let entry = entry_result.map_err(|e| FileOpsError::ArchiveExtractionFailed {
+            message: format!("Failed to read archive entry: {e}"),
+        })?;
+
+        if entry.header().entry_type().is_file() {
+            let path = entry
+                .path()
+                .map_err(|e| FileOpsError::ArchiveExtractionFailed {
+                    message: format!("Failed to get entry path: {e}"),
+                })?
+                .to_path_buf();
+            paths.push(path);
+        }
+    }
+
+    Ok(paths)
+}
+
+/// Apply the path limit strategy to audited entries
+///
+/// Returns the entries to shorten. Fails with the full list of offending
+/// entries when the strategy cannot remediate them.
+fn plan_path_limit_remediation(
+    limits: &PathLimits,
+    write_root: &Path,
+    entry_paths: &[PathBuf],
+    strategy: PathLimitStrategy,
+) -> Result<HashSet<PathBuf>> {
+    let violations = audit_entry_paths(limits, write_root, entry_paths);
+    if violations.is_empty() {
+        return Ok(HashSet::new());
+    }
+
+    if strategy != PathLimitStrategy::ShortenWithHashSuffix {
+        return Err(FileOpsError::PathLimitExceeded { violations });
+    }
+
+    // A placeholder tag of the final length proves a shortened name will fit
+    let placeholder_tag = "0".repeat(SHORTENED_NAME_HASH_LEN);
+    let (fixable, unfixable): (Vec<_>, Vec<_>) = violations.into_iter().partition(|v| {
+        shortened_relative_path(limits, write_root, &v.entry_path, &placeholder_tag).is_some()
+    });
+
+    if !unfixable.is_empty() {
+        return Err(FileOpsError::PathLimitExceeded {
+            violations: unfixable,
+        });
+    }
+
+    Ok(fixable.into_iter().map(|v| v.entry_path).collect())
+}
+
+/// Shortened relative path for an entry, if one fits within the limits
+fn shortened_relative_path(
+    limits: &PathLimits,
+    write_root: &Path,
+    entry: &Path,
+    tag: &str,
+) -> Option<PathBuf> {
+    let parent = shorten_parent_path(limits, entry)?;
+    let file_name = entry.file_name()?.to_string_lossy();
+    let budget = file_name_budget(limits, limits.measure_path(write_root), &parent);
+    let name = shorten_name(limits.platform, &file_name, budget, tag)?;
+    Some(parent.join(name))
+}
+
+/// Rename a temporarily written entry to its final shortened name
+///
+/// The content hash keeps distinct files unique; identical content that
+/// shortens to the same name gets a numeric suffix.
+fn finalize_shortened_entry(
+    limits: &PathLimits,
+    write_root: &Path,
+    entry: &Path,
+    temp_path: &Path,
+    content_hash: &str,
+    assigned_paths: &HashSet<PathBuf>,
+) -> Result<PathBuf> {
+    let hash_tag = &content_hash[..SHORTENED_NAME_HASH_LEN.min(content_hash.len())];
+
+    let mut attempt = 1;
+    let final_path = loop {
+        let tag = if attempt == 1 {
+            hash_tag.to_string()
+        } else {
+            format!("{hash_tag}-{attempt}")
+        };
+
+        let relative =
+            shortened_relative_path(limits, write_root, entry, &tag).ok_or_else(|| {
+                FileOpsError::PathValidationFailed {
+                    path: entry.to_path_buf(),
+                    reason: "No room to shorten entry name within path limits".to_string(),
+                }
+            })?;
+
+        let candidate = write_root.join(relative);
+        if !assigned_paths.contains(&candidate) {
+            break candidate;
+        }
+        attempt += 1;
+    };
+
+    fs::rename(temp_path, &final_path).map_err(|e| FileOpsError::IoError {
+        message: format!("Failed to rename shortened entry: {e}"),
+        source: e,
+    })?;
+
+    Ok(final_path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::file::infrastructure::file_operations::{
+        ArchiveSafetyLimits, RestoreFilter, SafetyLimit,
+    };
+    use flate2::Compression;
+    use flate2::write::GzEncoder;
+    use tar::{Builder, Header};
+    use tempfile::TempDir;
+
+    /// Build a tar.gz with the given (path, content) entries
+    ///
+    /// `append_data` writes GNU long-name records, so entries may exceed the
+    /// 100-byte ustar name field.
+    fn build_archive(dir: &Path, entries: &[(String, &[u8])]) -> PathBuf {
+        let archive_path = dir.join("test.tar.gz");
+        let file = File::create(&archive_path).unwrap();
+        let mut builder = Builder::new(GzEncoder::new(file, Compression::default()));
+
+        for (path, content) in entries {
+            let mut header = Header::new_gnu();
+            header.set_size(content.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder.append_data(&mut header, path, *content).unwrap();
+        }
+
+        builder.into_inner().unwrap().finish().unwrap();
+        archive_path
+    }
+
+    fn long_name(prefix: &str, len: usize) -> String {
+        format!("{prefix}{}.txt", "n".repeat(len - prefix.len() - 4))
+    }
+
+    #[test]
+    fn test_fail_strategy_reports_all_entries_and_writes_nothing() {
+        let temp = TempDir::new().unwrap();
+        let first = long_name("first", 300);
+        let second = format!("docs/{}", long_name("second", 300));
+        let archive = build_archive(
+            temp.path(),
+            &[
+                ("ok.txt".to_string(), b"ok"),
+                (first.clone(), b"one"),
+                (second.clone(), b"two"),
+            ],
+        );
+        let output = temp.path().join("out");
+
+        let result = extract_archive(&archive, &output, &FileOpsConfig::default());
+
+        match result {
+            Err(FileOpsError::PathLimitExceeded { violations }) => {
+                let paths: Vec<_> = violations.iter().map(|v| v.entry_path.clone()).collect();
+                assert_eq!(paths, vec![PathBuf::from(first), PathBuf::from(second)]);
+            }
+            other => panic!("expected PathLimitExceeded, got {other:?}"),
+        }
+        assert!(!output.join("ok.txt").exists());
+    }
+
+    #[test]
+    fn test_shorten_strategy_records_mapping() {
+        let temp = TempDir::new().unwrap();
+        let long = long_name("report", 300);
+        let archive = build_archive(
+            temp.path(),
+            &[("ok.txt".to_string(), b"ok"), (long.clone(), b"content")],
+        );
+        let output = temp.path().join("out");
+        let config = FileOpsConfig {
+            path_limit_strategy: PathLimitStrategy::ShortenWithHashSuffix,
+            ..FileOpsConfig::default()
+        };
+
+        let result = extract_archive_with_report(&archive, &output, &config).unwrap();
+
+        assert_eq!(result.files.len(), 2);
+        assert_eq!(result.renamed_paths.len(), 1);
+
+        let mapping = &result.renamed_paths[0];
+        assert_eq!(mapping.original_path, PathBuf::from(&long));
+
+        let new_name = mapping
+            .extracted_path
+            .file_name()
+            .unwrap()
+            .to_string_lossy()
+            .to_string();
+        let hash = calculate_file_hash(&mapping.extracted_path).unwrap();
+        assert!(new_name.len() <= 255);
+        assert!(new_name.starts_with("report"));
+        assert!(new_name.ends_with(&format!("~{}.txt", &hash[..8])));
+        assert_eq!(fs::read(&mapping.extracted_path).unwrap(), b"content");
+
+        // No temporary files left behind
+        let names: Vec<_> = fs::read_dir(&output)
+            .unwrap()
+            .map(|e| e.unwrap().file_name().to_string_lossy().to_string())
+            .collect();
+        assert!(names.iter().all(|n| !n.starts_with(".~")));
+    }
+
+    #[test]
+    fn test_shorten_strategy_keeps_names_unique() {
+        let temp = TempDir::new().unwrap();
+        // Same 250-char prefix, different tails: truncation alone would collide
+        let shared = "s".repeat(250);
+        let a = format!("{shared}-alpha.txt");
+        let b = format!("{shared}-beta.txt");
+        let c = format!("{shared}-gamma.txt");
+        let archive = build_archive(
+            temp.path(),
+            &[
+                (a.clone(), b"alpha"),
+                (b.clone(), b"beta"),
+                (c.clone(), b"alpha"),
+            ],
+        );
+        let config = FileOpsConfig {
+            path_limit_strategy: PathLimitStrategy::ShortenWithHashSuffix,
+            ..FileOpsConfig::default()
+        };
+
+        let result =
+            extract_archive_with_report(&archive, &temp.path().join("out"), &config).unwrap();
+
+        assert_eq!(result.renamed_paths.len(), 3);
+        let targets: HashSet<_> = result
+            .renamed_paths
+            .iter()
+            .map(|m| m.extracted_path.clone())
+            .collect();
+        assert_eq!(targets.len(), 3);
+
+        for mapping in &result.renamed_paths {
+            let expected: &[u8] = if mapping.original_path == PathBuf::from(&b) {
+                b"beta"
+            } else {
+                b"alpha"
+            };
+            assert_eq!(fs::read(&mapping.extracted_path).unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn test_shorten_strategy_shortens_long_directories() {
+        let temp = TempDir::new().unwrap();
+        let long_dir = "d".repeat(300);
+        let archive = build_archive(
+            temp.path(),
+            &[
+                (format!("{long_dir}/a.txt"), b"a"),
+                (format!("{long_dir}/b.txt"), b"b"),
+            ],
+        );
+        let config = FileOpsConfig {
+            path_limit_strategy: PathLimitStrategy::ShortenWithHashSuffix,
+            ..FileOpsConfig::default()
+        };
+
+        let result =
+            extract_archive_with_report(&archive, &temp.path().join("out"), &config).unwrap();
+
+        assert_eq!(result.renamed_paths.len(), 2);
+        let parents: HashSet<_> = result
+            .renamed_paths
+            .iter()
+            .map(|m| m.extracted_path.parent().unwrap().to_path_buf())
+            .collect();
+        assert_eq!(parents.len(), 1);
+    }
+
+    #[cfg(not(windows))]
+    #[test]
+    fn test_extended_paths_fall_back_to_fail_off_windows() {
+        let temp = TempDir::new().unwrap();
+        let archive = build_archive(temp.path(), &[(long_name("x", 300), b"x")]);
+        let config = FileOpsConfig {
+            path_limit_strategy: PathLimitStrategy::UseExtendedPaths,
+            ..FileOpsConfig::default()
+        };
+
+        let result = extract_archive(&archive, &temp.path().join("out"), &config);
+        assert!(matches!(
+            result,
+            Err(FileOpsError::PathLimitExceeded { .. })
+        ));
+    }
+
+    #[test]
+    fn test_archive_within_limits_is_unchanged() {
+        let temp = TempDir::new().unwrap();
+        let archive = build_archive(
+            temp.path(),
+            &[
+                ("a.txt".to_string(), b"a"),
+                ("nested/b.txt".to_string(), b"b"),
+            ],
+        );
+        let output = temp.path().join("out");
+
+        let result =
+            extract_archive_with_report(&archive, &output, &FileOpsConfig::default()).unwrap();
+
+        assert_eq!(result.files.len(), 2);
+        assert!(result.renamed_paths.is_empty());
+        assert!(output.join("nested").join("b.txt").exists());
+    }
+
+    #[test]
+    fn test_restore_filter_selects_by_detected_type() {
+        let temp = TempDir::new().unwrap();
+        let archive = build_archive(
+            temp.path(),
+            &[
+                ("photos/cat.png".to_string(), b"\x89PNG\r\n\x1a\n\x00\x00"),
+                (
+                    "photos/receipt.txt".to_string(),
+                    b"\xFF\xD8\xFF\xE0\x00\x10JFIF",
+                ),
+                ("docs/scan.jpg".to_string(), b"%PDF-1.7\n"),
+                ("docs/notes.txt".to_string(), b"plain notes"),
+                ("vault.manifest".to_string(), b"{}"),
+            ],
+        );
+        let output = temp.path().join("out");
+        let config = FileOpsConfig {
+            restore_filter: RestoreFilter {
+                paths: vec![],
+                content_types: vec!["image/*".to_string()],
+            },
+            ..FileOpsConfig::default()
+        };
+
+        let result = extract_archive_with_report(&archive, &output, &config).unwrap();
+
+        assert_eq!(result.files.len(), 3);
+        assert_eq!(result.skipped_by_filter, 2);
+        assert!(output.join("photos/cat.png").exists());
+        assert!(output.join("photos/receipt.txt").exists());
+        assert!(!output.join("docs/scan.jpg").exists());
+        assert!(output.join("vault.manifest").exists());
+        assert_eq!(
+            fs::read(output.join("photos/cat.png")).unwrap(),
+            b"\x89PNG\r\n\x1a\n\x00\x00"
+        );
+    }
+
+    #[test]
+    fn test_restore_filter_combines_paths_and_types() {
+        let temp = TempDir::new().unwrap();
+        let archive = build_archive(
+            temp.path(),
+            &[
+                ("docs/scan.jpg".to_string(), b"%PDF-1.7\n"),
+                ("docs/notes.txt".to_string(), b"plain notes"),
+                ("other/readme.txt".to_string(), b"readme"),
+            ],
+        );
+        let output = temp.path().join("out");
+        let config = FileOpsConfig {
+            restore_filter: RestoreFilter {
+                paths: vec!["other".to_string()],
+                content_types: vec!["application/pdf".to_string()],
+            },
+            ..FileOpsConfig::default()
+        };
+
+        let result = extract_archive_with_report(&archive, &output, &config).unwrap();
+
+        assert_eq!(result.files.len(), 2);
+        assert!(output.join("docs/scan.jpg").exists());
+        assert!(output.join("other/readme.txt").exists());
+        assert!(!output.join("docs/notes.txt").exists());
+    }
+
+    #[test]
+    fn test_prescan_is_skipped_only_for_recorded_limits() {
+        let temp = TempDir::new().unwrap();
+        let archive = build_archive(
+            temp.path(),
+            &[
+                ("a.txt".to_string(), b"a"),
+                ("b.txt".to_string(), b"b"),
+                ("c.txt".to_string(), b"c"),
+            ],
+        );
+        let output = temp.path().join("out");
+        let limits = ArchiveSafetyLimits {
+            max_entries: 2,
+            ..ArchiveSafetyLimits::default()
+        };
+        let unrecorded = FileOpsConfig {
+            safety_limits: limits,
+            ..FileOpsConfig::default()
+        };
+
+        let result = extract_archive_with_report(&archive, &output, &unrecorded);
+
+        assert!(matches!(
+            result,
+            Err(FileOpsError::ArchiveExceedsSafetyLimits {
+                which: SafetyLimit::EntryCount,
+                observed: 3,
+                limit: 2,
+            })
+        ));
+        assert!(!output.exists());
+
+        // Looser recorded limits prove nothing, so the archive is still scanned
+        let looser = FileOpsConfig {
+            recorded_safety_limits: Some(ArchiveSafetyLimits::default()),
+            ..unrecorded.clone()
+        };
+        assert!(extract_archive_with_report(&archive, &output, &looser).is_err());
+
+        // Limits recorded at creation are trusted without a scan
+        let recorded = FileOpsConfig {
+            recorded_safety_limits: Some(limits),
+            ..unrecorded
+        };
+        let result = extract_archive_with_report(&archive, &output, &recorded).unwrap();
+        assert_eq!(result.files.len(), 3);
+    }
 }
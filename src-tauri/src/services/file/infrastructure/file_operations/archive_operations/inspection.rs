@@ -0,0 +1,150 @@
+//! Archive inspection functionality
+//!
+//! Reads individual entries out of a TAR.GZ archive without extracting it.
+//! Backup bundles store the vault manifest as their first entry, so reading it
+//! only has to decompress the start of the archive. Older bundles placed it
+//! after the user files; those are still found by scanning forward.
+
+use super::super::{FileOpsError, Result};
+use flate2::read::GzDecoder;
+use std::io::Read;
+use std::path::Component;
+use tar::Archive;
+use tracing::debug;
+
+/// Upper bound on an embedded manifest; anything larger is not a manifest
+const MAX_EMBEDDED_MANIFEST_SIZE: u64 = 16 * 1024 * 1024;
+
+/// Vault manifest found inside an archive
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EmbeddedManifest {
+    /// Entry name as stored in the archive (e.g. `family-docs.manifest`)
+    pub filename: String,
+    /// Raw manifest bytes
+    pub contents: Vec<u8>,
+}
+
+/// Read the vault manifest embedded in a decrypted TAR.GZ payload
+///
+/// Returns the first top-level `*.manifest` entry, or `None` for archives
+/// without one (shared bundles).
+pub fn read_embedded_manifest(archive_data: &[u8]) -> Result<Option<EmbeddedManifest>> {
+    let mut archive = Archive::new(GzDecoder::new(archive_data));
+    let entries = archive
+        .entries()
+        .map_err(|e| FileOpsError::ArchiveExtractionFailed {
+            message: format!("Failed to read archive entries: {e}"),
+        })?;
+
+    for (position, entry) in entries.enumerate() {
+        let mut entry = entry.map_err(|e| FileOpsError::ArchiveExtractionFailed {
+            message: format!("Failed to read archive entry: {e}"),
+        })?;
+
+        if !entry.header().entry_type().is_file() {
+            continue;
+        }
+
+        let path = entry
+            .path()
+            .map_err(|e| FileOpsError::InvalidArchiveFormat {
+                message: format!("Invalid entry path: {e}"),
+            })?
+            .into_owned();
+
+        let mut components = path.components();
+        let filename = match (components.next(), components.next()) {
+            (Some(Component::Normal(name)), None) => name.to_string_lossy().into_owned(),
+            _ => continue,
+        };
+
+        if !filename.ends_with(".manifest") {
+            continue;
+        }
+
+        let size = entry.header().size().unwrap_or(0);
+        if size > MAX_EMBEDDED_MANIFEST_SIZE {
+            return Err(FileOpsError::InvalidArchiveFormat {
+                message: format!("Embedded manifest {filename} is too large ({size} bytes)"),
+            });
+        }
+
+        let mut contents = Vec::with_capacity(size as usize);
+        entry
+            .read_to_end(&mut contents)
+            .map_err(|e| FileOpsError::IoError {
+                message: format!("Failed to read embedded manifest {filename}"),
+                source: e,
+            })?;
+
+        debug!(filename = %filename, position, "Found embedded manifest");
+        return Ok(Some(EmbeddedManifest { filename, contents }));
+    }
+
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::Compression;
+    use flate2::write::GzEncoder;
+    use tar::{Builder, Header};
+
+    fn build_archive(entries: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut builder = Builder::new(GzEncoder::new(Vec::new(), Compression::default()));
+
+        for (path, content) in entries {
+            let mut header = Header::new_gnu();
+            header.set_size(content.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder.append_data(&mut header, path, *content).unwrap();
+        }
+
+        builder.into_inner().unwrap().finish().unwrap()
+    }
+
+    #[test]
+    fn test_reads_manifest_stored_first() {
+        let archive = build_archive(&[
+            ("docs.manifest", b"{\"manifest\":true}"),
+            ("notes.txt", b"hello"),
+        ]);
+
+        let manifest = read_embedded_manifest(&archive).unwrap().unwrap();
+        assert_eq!(manifest.filename, "docs.manifest");
+        assert_eq!(manifest.contents, b"{\"manifest\":true}");
+    }
+
+    #[test]
+    fn test_reads_manifest_from_legacy_layout() {
+        let archive = build_archive(&[
+            ("notes.txt", b"hello"),
+            ("docs.manifest", b"{}"),
+            ("docs.agekey.enc", b"key"),
+        ]);
+
+        let manifest = read_embedded_manifest(&archive).unwrap().unwrap();
+        assert_eq!(manifest.filename, "docs.manifest");
+    }
+
+    #[test]
+    fn test_ignores_nested_manifest_files() {
+        let archive = build_archive(&[("folder/user.manifest", b"user data")]);
+
+        assert!(read_embedded_manifest(&archive).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_shared_bundle_has_no_manifest() {
+        let archive = build_archive(&[("notes.txt", b"hello")]);
+
+        assert!(read_embedded_manifest(&archive).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_rejects_corrupt_archive() {
+        assert!(read_embedded_manifest(b"not a gzip stream").is_err());
+    }
+}
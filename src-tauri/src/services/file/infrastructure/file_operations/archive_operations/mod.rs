@@ -5,9 +5,20 @@
 
 pub mod creation;
 pub mod extraction;
+pub mod inspection;
+pub mod preview;
+pub mod resume;
+pub mod salvage;
 
 // Re-export main functions for backward compatibility
 pub use creation::{
     create_archive, create_archive_with_file_info, create_archive_with_progress, create_tar_gz,
 };
-pub use extraction::extract_archive;
+pub use extraction::{ExtractionResult, PathMapping, extract_archive, extract_archive_with_report};
+pub use inspection::{EmbeddedManifest, read_embedded_manifest};
+pub use preview::{
+    EntryPreview, PreviewContent, PreviewImageFormat, PreviewLimits, PreviewOmission,
+    preview_entries,
+};
+pub use resume::{ExtractionJournal, ExtractionPolicy, RESUME_JOURNAL_SUFFIX};
+pub use salvage::{PARTIAL_FILE_SUFFIX, SalvagedEntry, TarSalvage, salvage_tar_gz};
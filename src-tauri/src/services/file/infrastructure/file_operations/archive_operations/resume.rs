@@ -0,0 +1,492 @@
+//! Journal for resuming an interrupted extraction
+//!
+//! Extracting a very large archive can be cut short by a crash, a full disk
+//! or the app being closed. The journal, kept beside the output directory,
+//! names the archive and the extraction policy, then records every file once
+//! it is fully written, together with its hash. Extracting the same archive
+//! into the same directory again checks the recorded files and writes only
+//! the ones that are missing or changed.
+//!
+//! Records are appended as JSON lines without syncing: a torn or lost final
+//! line only means that file is extracted again. Recorded files are hashed
+//! again before being kept, so the journal never vouches for contents alone.
+
+use super::super::utils::calculate_file_hash;
+use super::super::validation::{PathLimitStrategy, contains_traversal_attempt};
+use super::super::{FileOpsError, RestoreFilter, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tracing::{debug, info, warn};
+
+/// Suffix of the journal file kept beside the output directory
+pub const RESUME_JOURNAL_SUFFIX: &str = ".restore-journal";
+
+/// Settings a resumed extraction must share with the interrupted one
+///
+/// They decide which entries are written and under which names, so files
+/// written under different settings can't be kept.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExtractionPolicy {
+    pub path_limit_strategy: PathLimitStrategy,
+    pub restore_filter: RestoreFilter,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "record", rename_all = "snake_case")]
+enum JournalRecord {
+    Started {
+        archive_sha256: String,
+        policy: ExtractionPolicy,
+    },
+    Extracted {
+        entry: PathBuf,
+        /// Relative to the output directory
+        path: PathBuf,
+        sha256: String,
+    },
+}
+
+/// A file recorded as fully written
+#[derive(Debug, Clone)]
+struct CompletedFile {
+    path: PathBuf,
+    sha256: String,
+}
+
+/// Contents of a journal file on disk
+struct JournalContents {
+    archive_sha256: String,
+    policy: ExtractionPolicy,
+    completed: HashMap<PathBuf, CompletedFile>,
+}
+
+/// Injected crash point for tests: stop after this many recorded files
+#[cfg(test)]
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct FailAfterFiles(pub usize);
+
+#[derive(Debug)]
+struct JournalWriter {
+    file: File,
+    recorded: usize,
+}
+
+/// Progress journal of one extraction
+#[derive(Debug)]
+pub struct ExtractionJournal {
+    path: PathBuf,
+    completed: HashMap<PathBuf, CompletedFile>,
+    writer: Mutex<JournalWriter>,
+    #[cfg(test)]
+    fail_after: Option<FailAfterFiles>,
+}
+
+impl ExtractionJournal {
+    /// Where the journal for `output_dir` is kept
+    pub fn path_for(output_dir: &Path) -> PathBuf {
+        match output_dir.file_name() {
+            Some(name) => output_dir.with_file_name(format!(
+                ".{}{RESUME_JOURNAL_SUFFIX}",
+                name.to_string_lossy()
+            )),
+            None => output_dir.join(RESUME_JOURNAL_SUFFIX),
+        }
+    }
+
+    /// Whether an interrupted extraction into `output_dir` left a journal
+    pub fn exists(output_dir: &Path) -> bool {
+        Self::path_for(output_dir).is_file()
+    }
+
+    /// Whether the journal for `output_dir` was started for this archive and policy
+    pub fn matches(output_dir: &Path, archive_sha256: &str, policy: &ExtractionPolicy) -> bool {
+        read_journal(&Self::path_for(output_dir)).is_some_and(|journal| {
+            journal.archive_sha256 == archive_sha256 && journal.policy == *policy
+        })
+    }
+
+    /// Open the journal for extracting the archive `archive_sha256` into `output_dir`
+    ///
+    /// A journal left by the same archive and policy is continued; any other
+    /// is discarded and a new one started.
+    pub fn open(
+        output_dir: &Path,
+        archive_sha256: &str,
+        policy: &ExtractionPolicy,
+    ) -> Result<Self> {
+        let path = Self::path_for(output_dir);
+
+        let completed = match read_journal(&path) {
+            Some(journal)
+                if journal.archive_sha256 == archive_sha256 && journal.policy == *policy =>
+            {
+                Some(journal.completed)
+            }
+            Some(_) => {
+                warn!(
+                    "Discarding restore journal left by a different archive or policy: {}",
+                    path.display()
+                );
+                None
+            }
+            None => None,
+        };
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| journal_error("create", e))?;
+        }
+
+        let (file, completed) = match completed {
+            Some(completed) => {
+                let mut file = OpenOptions::new()
+                    .read(true)
+                    .append(true)
+                    .open(&path)
+                    .map_err(|e| journal_error("open", e))?;
+                end_torn_line(&mut file)?;
+                info!(
+                    files = completed.len(),
+                    "Resuming interrupted extraction: {}",
+                    output_dir.display()
+                );
+                (file, completed)
+            }
+            None => {
+                let mut file = File::create(&path).map_err(|e| journal_error("create", e))?;
+                write_record(
+                    &mut file,
+                    &JournalRecord::Started {
+                        archive_sha256: archive_sha256.to_string(),
+                        policy: policy.clone(),
+                    },
+                )?;
+                (file, HashMap::new())
+            }
+        };
+
+        Ok(Self {
+            path,
+            completed,
+            writer: Mutex::new(JournalWriter { file, recorded: 0 }),
+            #[cfg(test)]
+            fail_after: None,
+        })
+    }
+
+    #[cfg(test)]
+    pub(crate) fn with_fail_point(mut self, fail_after: FailAfterFiles) -> Self {
+        self.fail_after = Some(fail_after);
+        self
+    }
+
+    #[cfg(test)]
+    fn hits_fail_point(&self, recorded: usize) -> bool {
+        self.fail_after.is_some_and(|f| f.0 == recorded)
+    }
+
+    #[cfg(not(test))]
+    fn hits_fail_point(&self, _recorded: usize) -> bool {
+        false
+    }
+
+    /// Files the interrupted extraction recorded
+    pub fn recorded_files(&self) -> usize {
+        self.completed.len()
+    }
+
+    /// Where `entry` was written by the interrupted extraction, if still intact
+    ///
+    /// Returns the path under `write_root` and the file's hash.
+    pub(super) fn verified(&self, entry: &Path, write_root: &Path) -> Option<(PathBuf, String)> {
+        let completed = self.completed.get(entry)?;
+        let path = write_root.join(&completed.path);
+        match calculate_file_hash(&path) {
+            Ok(hash) if hash == completed.sha256 => Some((path, hash)),
+            _ => {
+                info!("Re-extracting missing or changed file: {}", entry.display());
+                None
+            }
+        }
+    }
+
+    /// Record `entry` as fully written to `path`, relative to the output directory
+    pub(super) fn record(&self, entry: &Path, path: &Path, sha256: &str) -> Result<()> {
+        let mut writer = self
+            .writer
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        write_record(
+            &mut writer.file,
+            &JournalRecord::Extracted {
+                entry: entry.to_path_buf(),
+                path: path.to_path_buf(),
+                sha256: sha256.to_string(),
+            },
+        )?;
+        writer.recorded += 1;
+
+        if self.hits_fail_point(writer.recorded) {
+            return Err(FileOpsError::ArchiveExtractionFailed {
+                message: "Injected failure point".to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Remove the journal once extraction has completed (best effort)
+    pub fn finish(&self) {
+        match fs::remove_file(&self.path) {
+            Ok(()) => debug!("Removed restore journal: {}", self.path.display()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => warn!(
+                "Failed to remove restore journal {}: {e}",
+                self.path.display()
+            ),
+        }
+    }
+}
+
+/// Read a journal, or `None` if it is missing or has no usable header
+fn read_journal(path: &Path) -> Option<JournalContents> {
+    let file = File::open(path).ok()?;
+    let mut lines = BufReader::new(file).lines();
+
+    let JournalRecord::Started {
+        archive_sha256,
+        policy,
+    } = serde_json::from_str(&lines.next()?.ok()?).ok()?
+    else {
+        return None;
+    };
+
+    let mut completed = HashMap::new();
+    for line in lines {
+        let Ok(line) = line else { break };
+        // A torn final line is the file being written when the run stopped
+        let Ok(JournalRecord::Extracted {
+            entry,
+            path,
+            sha256,
+        }) = serde_json::from_str(&line)
+        else {
+            continue;
+        };
+        // Recorded paths must stay inside the output directory
+        if path.is_absolute() || contains_traversal_attempt(&path) {
+            warn!("Ignoring restore journal record outside the output directory");
+            continue;
+        }
+        completed.insert(entry, CompletedFile { path, sha256 });
+    }
+
+    Some(JournalContents {
+        archive_sha256,
+        policy,
+        completed,
+    })
+}
+
+/// Terminate a torn final line so the next record starts on its own
+fn end_torn_line(file: &mut File) -> Result<()> {
+    let mut last = [0u8; 1];
+    let torn = file
+        .seek(SeekFrom::End(-1))
+        .and_then(|_| file.read_exact(&mut last))
+        .is_ok_and(|_| last[0] != b'\n');
+    if torn {
+        file.write_all(b"\n")
+            .map_err(|e| journal_error("write", e))?;
+    }
+    Ok(())
+}
+
+fn write_record(file: &mut File, record: &JournalRecord) -> Result<()> {
+    let mut line = serde_json::to_string(record).map_err(|e| journal_error("encode", e.into()))?;
+    line.push('\n');
+    file.write_all(line.as_bytes())
+        .map_err(|e| journal_error("write", e))
+}
+
+fn journal_error(action: &str, e: std::io::Error) -> FileOpsError {
+    FileOpsError::IoError {
+        message: format!("Failed to {action} restore journal: {e}"),
+        source: e,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::extraction::extract_archive_with_report;
+    use super::*;
+    use crate::services::file::infrastructure::file_operations::FileOpsConfig;
+    use flate2::Compression;
+    use flate2::write::GzEncoder;
+    use std::sync::Arc;
+    use std::time::{Duration, UNIX_EPOCH};
+    use tar::{Builder, Header};
+    use tempfile::TempDir;
+
+    const ARCHIVE_HASH: &str = "a1";
+
+    fn entries() -> Vec<(String, Vec<u8>)> {
+        (0..5)
+            .map(|i| {
+                (
+                    format!("docs/file-{i}.bin"),
+                    (0..4096).map(|b| ((b * (i + 1)) % 251) as u8).collect(),
+                )
+            })
+            .collect()
+    }
+
+    fn build_archive(dir: &Path, entries: &[(String, Vec<u8>)]) -> PathBuf {
+        let archive_path = dir.join("test.tar.gz");
+        let file = File::create(&archive_path).unwrap();
+        let mut builder = Builder::new(GzEncoder::new(file, Compression::default()));
+        for (path, content) in entries {
+            let mut header = Header::new_gnu();
+            header.set_size(content.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder
+                .append_data(&mut header, path, content.as_slice())
+                .unwrap();
+        }
+        builder.into_inner().unwrap().finish().unwrap();
+        archive_path
+    }
+
+    fn config_with(journal: ExtractionJournal) -> FileOpsConfig {
+        FileOpsConfig {
+            resume_journal: Some(Arc::new(journal)),
+            ..FileOpsConfig::default()
+        }
+    }
+
+    #[test]
+    fn test_resume_keeps_completed_files() {
+        let temp = TempDir::new().unwrap();
+        let entries = entries();
+        let archive = build_archive(temp.path(), &entries);
+        let output = temp.path().join("out");
+        let policy = ExtractionPolicy::default();
+
+        let journal = ExtractionJournal::open(&output, ARCHIVE_HASH, &policy)
+            .unwrap()
+            .with_fail_point(FailAfterFiles(2));
+        assert!(extract_archive_with_report(&archive, &output, &config_with(journal)).is_err());
+        assert!(ExtractionJournal::exists(&output));
+
+        // Backdate the completed files so a rewrite would show
+        let old = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        for (path, _) in &entries[..2] {
+            File::options()
+                .write(true)
+                .open(output.join(path))
+                .unwrap()
+                .set_modified(old)
+                .unwrap();
+        }
+
+        assert!(ExtractionJournal::matches(&output, ARCHIVE_HASH, &policy));
+        let journal = ExtractionJournal::open(&output, ARCHIVE_HASH, &policy).unwrap();
+        assert_eq!(journal.recorded_files(), 2);
+        let config = config_with(journal);
+        let result = extract_archive_with_report(&archive, &output, &config).unwrap();
+        config.resume_journal.unwrap().finish();
+
+        assert_eq!(result.files.len(), entries.len());
+        assert_eq!(result.resumed, 2);
+        for (index, (path, content)) in entries.iter().enumerate() {
+            let written = output.join(path);
+            assert_eq!(&fs::read(&written).unwrap(), content);
+            let modified = fs::metadata(&written).unwrap().modified().unwrap();
+            assert_eq!(modified == old, index < 2, "{path}");
+        }
+        assert!(!ExtractionJournal::exists(&output));
+    }
+
+    #[test]
+    fn test_changed_file_is_extracted_again() {
+        let temp = TempDir::new().unwrap();
+        let entries = entries();
+        let archive = build_archive(temp.path(), &entries);
+        let output = temp.path().join("out");
+        let policy = ExtractionPolicy::default();
+
+        let journal = ExtractionJournal::open(&output, ARCHIVE_HASH, &policy)
+            .unwrap()
+            .with_fail_point(FailAfterFiles(3));
+        assert!(extract_archive_with_report(&archive, &output, &config_with(journal)).is_err());
+        fs::write(output.join(&entries[1].0), b"tampered").unwrap();
+
+        let journal = ExtractionJournal::open(&output, ARCHIVE_HASH, &policy).unwrap();
+        let result = extract_archive_with_report(&archive, &output, &config_with(journal)).unwrap();
+
+        assert_eq!(result.resumed, 2);
+        assert_eq!(fs::read(output.join(&entries[1].0)).unwrap(), entries[1].1);
+    }
+
+    #[test]
+    fn test_other_archive_discards_journal() {
+        let temp = TempDir::new().unwrap();
+        let entries = entries();
+        let archive = build_archive(temp.path(), &entries);
+        let output = temp.path().join("out");
+        let policy = ExtractionPolicy::default();
+
+        let journal = ExtractionJournal::open(&output, ARCHIVE_HASH, &policy)
+            .unwrap()
+            .with_fail_point(FailAfterFiles(2));
+        assert!(extract_archive_with_report(&archive, &output, &config_with(journal)).is_err());
+
+        assert!(!ExtractionJournal::matches(&output, "b2", &policy));
+        let other_policy = ExtractionPolicy {
+            path_limit_strategy: PathLimitStrategy::ShortenWithHashSuffix,
+            ..ExtractionPolicy::default()
+        };
+        assert!(!ExtractionJournal::matches(
+            &output,
+            ARCHIVE_HASH,
+            &other_policy
+        ));
+
+        let journal = ExtractionJournal::open(&output, "b2", &policy).unwrap();
+        assert_eq!(journal.recorded_files(), 0);
+        let result = extract_archive_with_report(&archive, &output, &config_with(journal)).unwrap();
+        assert_eq!(result.resumed, 0);
+    }
+
+    #[test]
+    fn test_torn_and_escaping_records_are_ignored() {
+        let temp = TempDir::new().unwrap();
+        let output = temp.path().join("out");
+        let policy = ExtractionPolicy::default();
+        drop(ExtractionJournal::open(&output, ARCHIVE_HASH, &policy).unwrap());
+
+        let mut file = OpenOptions::new()
+            .append(true)
+            .open(ExtractionJournal::path_for(&output))
+            .unwrap();
+        writeln!(
+            file,
+            r#"{{"record":"extracted","entry":"a.txt","path":"../a.txt","sha256":"00"}}"#
+        )
+        .unwrap();
+        write!(file, r#"{{"record":"extracted","entry":"b.txt""#).unwrap();
+
+        let journal = ExtractionJournal::open(&output, ARCHIVE_HASH, &policy).unwrap();
+        assert_eq!(journal.recorded_files(), 0);
+
+        // Records after the torn line start on a line of their own
+        journal
+            .record(Path::new("c.txt"), Path::new("c.txt"), "00")
+            .unwrap();
+        let journal = ExtractionJournal::open(&output, ARCHIVE_HASH, &policy).unwrap();
+        assert_eq!(journal.recorded_files(), 1);
+    }
+}
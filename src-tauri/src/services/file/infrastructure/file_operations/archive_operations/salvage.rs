@@ -0,0 +1,274 @@
+//! Tolerant extraction of damaged archives
+//!
+//! Reads as much of a TAR.GZ payload as survives and reports each file entry
+//! as complete or truncated, instead of failing on the first bad byte. The
+//! payload is a single gzip stream, so nothing after the first damaged byte
+//! can be inflated: only entries that start before the damage are found.
+//!
+//! Truncated entries are written as `<name>.partial` so they can't be
+//! mistaken for the real file.
+
+use super::super::validation::contains_traversal_attempt;
+use super::super::validation::path_limits::native_relative_path;
+use super::super::{FileOpsError, Result};
+use super::extraction::is_internal_entry;
+use super::inspection::EmbeddedManifest;
+use flate2::read::GzDecoder;
+use sha2::{Digest, Sha256};
+use std::ffi::OsString;
+use std::fs;
+use std::io::Read;
+use std::path::{Component, Path, PathBuf};
+use tar::Archive;
+use tracing::{debug, info, warn};
+
+/// Suffix for files written from truncated entries
+pub const PARTIAL_FILE_SUFFIX: &str = ".partial";
+
+/// One file entry found in a damaged archive
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SalvagedEntry {
+    /// Entry path as stored in the archive
+    pub path: PathBuf,
+    /// Size recorded in the entry header
+    pub expected_size: u64,
+    /// Bytes of content that survived
+    pub recovered_bytes: u64,
+    /// SHA-256 of the content, for complete entries only
+    pub sha256: Option<String>,
+    /// Where the entry was written, if an output directory was given
+    pub written_to: Option<PathBuf>,
+    /// Vault manifest or key file rather than a user file
+    pub internal: bool,
+}
+
+impl SalvagedEntry {
+    pub fn is_complete(&self) -> bool {
+        self.recovered_bytes == self.expected_size
+    }
+}
+
+/// What a tolerant read of a damaged TAR.GZ payload found
+#[derive(Debug, Clone, Default)]
+pub struct TarSalvage {
+    /// File entries in archive order
+    pub entries: Vec<SalvagedEntry>,
+    /// Embedded vault manifest, if it survived intact
+    pub manifest: Option<EmbeddedManifest>,
+}
+
+/// Recover the file entries of the undamaged start of a TAR.GZ payload
+///
+/// `intact_prefix` must hold only authentic bytes; anything after the first
+/// damaged byte would inflate to garbage. With `output_dir`, complete entries
+/// are written to their archive path and truncated ones to
+/// `<path>.partial`; without it this is a dry run.
+pub fn salvage_tar_gz(intact_prefix: &[u8], output_dir: Option<&Path>) -> Result<TarSalvage> {
+    let inflated = inflate_prefix(intact_prefix);
+    debug!(
+        intact_bytes = intact_prefix.len(),
+        inflated_bytes = inflated.len(),
+        "Inflated intact archive prefix"
+    );
+
+    let mut salvage = TarSalvage::default();
+    let mut archive = Archive::new(inflated.as_slice());
+    let Ok(entries) = archive.entries() else {
+        return Ok(salvage);
+    };
+
+    for entry in entries {
+        // A header cut short or overwritten ends the readable part
+        let Ok(mut entry) = entry else { break };
+        if !entry.header().entry_type().is_file() {
+            continue;
+        }
+        let Ok(path) = entry.path().map(|p| p.into_owned()) else {
+            break;
+        };
+        if contains_traversal_attempt(&path) {
+            warn!(path = %path.display(), "Skipping unsafe entry in damaged archive");
+            continue;
+        }
+
+        let expected_size = entry.header().size().unwrap_or(0);
+        let mut contents = Vec::new();
+        let mut buf = [0u8; 64 * 1024];
+        while let Ok(n) = entry.read(&mut buf) {
+            if n == 0 {
+                break;
+            }
+            contents.extend_from_slice(&buf[..n]);
+        }
+        let complete = contents.len() as u64 == expected_size;
+
+        let written_to = match output_dir {
+            Some(dir) => Some(write_entry(dir, &path, &contents, complete)?),
+            None => None,
+        };
+        let sha256 = complete.then(|| hex::encode(Sha256::digest(&contents)));
+
+        if complete && salvage.manifest.is_none() {
+            salvage.manifest = top_level_manifest(&path, &contents);
+        }
+        salvage.entries.push(SalvagedEntry {
+            internal: is_internal_entry(&path),
+            path,
+            expected_size,
+            recovered_bytes: contents.len() as u64,
+            sha256,
+            written_to,
+        });
+        if !complete {
+            break;
+        }
+    }
+
+    info!(
+        entries = salvage.entries.len(),
+        complete = salvage.entries.iter().filter(|e| e.is_complete()).count(),
+        "Salvaged entries from damaged archive"
+    );
+    Ok(salvage)
+}
+
+/// Inflate as much of a gzip stream as decodes, stopping at the first error
+fn inflate_prefix(data: &[u8]) -> Vec<u8> {
+    let mut decoder = GzDecoder::new(data);
+    let mut inflated = Vec::new();
+    let mut buf = [0u8; 64 * 1024];
+    while let Ok(n) = decoder.read(&mut buf) {
+        if n == 0 {
+            break;
+        }
+        inflated.extend_from_slice(&buf[..n]);
+    }
+    inflated
+}
+
+fn top_level_manifest(path: &Path, contents: &[u8]) -> Option<EmbeddedManifest> {
+    let mut components = path.components();
+    match (components.next(), components.next()) {
+        (Some(Component::Normal(name)), None) => {
+            let filename = name.to_string_lossy().into_owned();
+            filename.ends_with(".manifest").then(|| EmbeddedManifest {
+                filename,
+                contents: contents.to_vec(),
+            })
+        }
+        _ => None,
+    }
+}
+
+/// Write one salvaged entry under `output_dir`, checking it stays inside
+fn write_entry(output_dir: &Path, path: &Path, contents: &[u8], complete: bool) -> Result<PathBuf> {
+    let mut output_path = output_dir.join(native_relative_path(path));
+    if !complete {
+        let mut name = output_path
+            .file_name()
+            .map(OsString::from)
+            .unwrap_or_default();
+        name.push(PARTIAL_FILE_SUFFIX);
+        output_path.set_file_name(name);
+    }
+
+    let parent = output_path
+        .parent()
+        .ok_or_else(|| FileOpsError::PathValidationFailed {
+            path: output_path.clone(),
+            reason: "Invalid output path".to_string(),
+        })?;
+    fs::create_dir_all(parent).map_err(|e| FileOpsError::IoError {
+        message: format!("Failed to create parent directory: {e}"),
+        source: e,
+    })?;
+
+    let canonical_output_dir = output_dir
+        .canonicalize()
+        .unwrap_or_else(|_| output_dir.to_path_buf());
+    let canonical_parent =
+        parent
+            .canonicalize()
+            .map_err(|e| FileOpsError::PathValidationFailed {
+                path: parent.to_path_buf(),
+                reason: format!("Failed to resolve output parent directory: {e}"),
+            })?;
+    if !canonical_parent.starts_with(&canonical_output_dir) {
+        return Err(FileOpsError::PathValidationFailed {
+            path: output_path,
+            reason: "Archive entry would extract outside of output directory".to_string(),
+        });
+    }
+
+    fs::write(&output_path, contents).map_err(|e| FileOpsError::IoError {
+        message: format!("Failed to write salvaged file: {e}"),
+        source: e,
+    })?;
+    Ok(output_path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::Compression;
+    use flate2::write::GzEncoder;
+    use tar::{Builder, Header};
+    use tempfile::TempDir;
+
+    fn build_archive(entries: &[(&str, Vec<u8>)]) -> Vec<u8> {
+        let mut builder = Builder::new(GzEncoder::new(Vec::new(), Compression::none()));
+        for (path, content) in entries {
+            let mut header = Header::new_gnu();
+            header.set_size(content.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder
+                .append_data(&mut header, path, content.as_slice())
+                .unwrap();
+        }
+        builder.into_inner().unwrap().finish().unwrap()
+    }
+
+    #[test]
+    fn test_intact_archive_is_fully_recovered() {
+        let archive = build_archive(&[
+            ("docs.manifest", b"{}".to_vec()),
+            ("docs/a.txt", vec![b'a'; 1000]),
+        ]);
+
+        let salvage = salvage_tar_gz(&archive, None).unwrap();
+        assert_eq!(salvage.entries.len(), 2);
+        assert!(salvage.entries.iter().all(SalvagedEntry::is_complete));
+        assert_eq!(salvage.manifest.unwrap().filename, "docs.manifest");
+    }
+
+    #[test]
+    fn test_truncated_archive_reports_partial_entry() {
+        let archive = build_archive(&[
+            ("a.txt", vec![b'a'; 2000]),
+            ("b.txt", vec![b'b'; 20_000]),
+            ("c.txt", vec![b'c'; 2000]),
+        ]);
+        // Stored (level 0) gzip keeps offsets close to the tar layout
+        let truncated = &archive[..10_000];
+
+        let dir = TempDir::new().unwrap();
+        let salvage = salvage_tar_gz(truncated, Some(dir.path())).unwrap();
+
+        assert_eq!(salvage.entries.len(), 2);
+        assert!(salvage.entries[0].is_complete());
+        let partial = &salvage.entries[1];
+        assert_eq!(partial.path, Path::new("b.txt"));
+        assert!(!partial.is_complete());
+        assert!(partial.recovered_bytes > 0 && partial.recovered_bytes < 20_000);
+        assert!(partial.sha256.is_none());
+
+        assert_eq!(
+            fs::read(dir.path().join("a.txt")).unwrap(),
+            vec![b'a'; 2000]
+        );
+        let partial_file = fs::read(dir.path().join("b.txt.partial")).unwrap();
+        assert_eq!(partial_file.len() as u64, partial.recovered_bytes);
+        assert!(!dir.path().join("b.txt").exists());
+    }
+}
@@ -0,0 +1,372 @@
+//! Content type detection
+//!
+//! Identifies files by their leading bytes rather than their extension, so
+//! the manifest can record what a file really is and restores can be
+//! filtered by type (`image/*`, `application/pdf`). Only the first
+//! `CONTENT_SNIFF_LIMIT` bytes are read, and a file that can't be read simply
+//! has no recorded type.
+
+use crate::constants::CONTENT_SNIFF_LIMIT;
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::Read;
+use std::path::{Component, Path};
+
+/// Type used for binary content no signature matches
+pub const UNKNOWN_CONTENT_TYPE: &str = "application/octet-stream";
+
+/// Detected content type of a file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContentTypeInfo {
+    pub content_type: String,
+    /// The extension promises a different kind of file than the content
+    pub extension_mismatch: bool,
+}
+
+/// Leading-byte signatures, checked in order
+const SIGNATURES: &[(&[u8], &str)] = &[
+    (b"%PDF-", "application/pdf"),
+    (b"\x89PNG\r\n\x1a\n", "image/png"),
+    (b"\xFF\xD8\xFF", "image/jpeg"),
+    (b"GIF87a", "image/gif"),
+    (b"GIF89a", "image/gif"),
+    (b"II*\x00", "image/tiff"),
+    (b"MM\x00*", "image/tiff"),
+    (b"ID3", "audio/mpeg"),
+    (b"OggS", "audio/ogg"),
+    (b"fLaC", "audio/flac"),
+    (b"PK\x03\x04", "application/zip"),
+    (b"\x1F\x8B", "application/gzip"),
+    (b"7z\xBC\xAF\x27\x1C", "application/x-7z-compressed"),
+    (b"Rar!\x1A\x07", "application/vnd.rar"),
+    (b"age-encryption.org/v1", "application/x-age-encrypted"),
+    (
+        b"-----BEGIN AGE ENCRYPTED FILE-----",
+        "application/x-age-encrypted",
+    ),
+    (b"SQLite format 3\x00", "application/vnd.sqlite3"),
+    (b"\x7FELF", "application/x-executable"),
+    (b"MZ", "application/vnd.microsoft.portable-executable"),
+];
+
+/// Known extensions: the type they stand for, and what sniffing reports for
+/// a genuine file of that type (office documents are zip containers, most
+/// text formats have no signature)
+const EXTENSIONS: &[(&str, &str, &str)] = &[
+    ("pdf", "application/pdf", "application/pdf"),
+    ("png", "image/png", "image/png"),
+    ("jpg", "image/jpeg", "image/jpeg"),
+    ("jpeg", "image/jpeg", "image/jpeg"),
+    ("gif", "image/gif", "image/gif"),
+    ("webp", "image/webp", "image/webp"),
+    ("tif", "image/tiff", "image/tiff"),
+    ("tiff", "image/tiff", "image/tiff"),
+    ("heic", "image/heic", "image/heic"),
+    ("mp4", "video/mp4", "video/mp4"),
+    ("m4v", "video/mp4", "video/mp4"),
+    ("mov", "video/quicktime", "video/quicktime"),
+    ("mp3", "audio/mpeg", "audio/mpeg"),
+    ("ogg", "audio/ogg", "audio/ogg"),
+    ("flac", "audio/flac", "audio/flac"),
+    ("wav", "audio/wav", "audio/wav"),
+    ("zip", "application/zip", "application/zip"),
+    ("gz", "application/gzip", "application/gzip"),
+    ("tgz", "application/gzip", "application/gzip"),
+    (
+        "7z",
+        "application/x-7z-compressed",
+        "application/x-7z-compressed",
+    ),
+    ("rar", "application/vnd.rar", "application/vnd.rar"),
+    (
+        "age",
+        "application/x-age-encrypted",
+        "application/x-age-encrypted",
+    ),
+    (
+        "sqlite",
+        "application/vnd.sqlite3",
+        "application/vnd.sqlite3",
+    ),
+    ("db", "application/vnd.sqlite3", "application/vnd.sqlite3"),
+    (
+        "docx",
+        "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+        "application/zip",
+    ),
+    (
+        "xlsx",
+        "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+        "application/zip",
+    ),
+    (
+        "pptx",
+        "application/vnd.openxmlformats-officedocument.presentationml.presentation",
+        "application/zip",
+    ),
+    (
+        "odt",
+        "application/vnd.oasis.opendocument.text",
+        "application/zip",
+    ),
+    ("epub", "application/epub+zip", "application/zip"),
+    ("txt", "text/plain", "text/plain"),
+    ("md", "text/markdown", "text/plain"),
+    ("csv", "text/csv", "text/plain"),
+    ("json", "application/json", "text/plain"),
+    ("xml", "application/xml", "text/plain"),
+    ("html", "text/html", "text/plain"),
+    ("htm", "text/html", "text/plain"),
+    ("yaml", "application/yaml", "text/plain"),
+    ("yml", "application/yaml", "text/plain"),
+    ("log", "text/plain", "text/plain"),
+];
+
+/// Detect a file's content type from its first `CONTENT_SNIFF_LIMIT` bytes
+///
+/// Returns `None` for empty or unreadable files; callers record no type
+/// rather than failing.
+pub fn sniff_content_type(path: &Path) -> Option<ContentTypeInfo> {
+    let file = File::open(path).ok()?;
+    let mut head = Vec::with_capacity(CONTENT_SNIFF_LIMIT);
+    file.take(CONTENT_SNIFF_LIMIT as u64)
+        .read_to_end(&mut head)
+        .ok()?;
+    classify_content(path, &head)
+}
+
+/// Classify leading bytes, checking them against the name's extension
+pub fn classify_content(name: &Path, head: &[u8]) -> Option<ContentTypeInfo> {
+    let detected = detect_content_type(head)?;
+    let known = name
+        .extension()
+        .map(|ext| ext.to_string_lossy().to_ascii_lowercase())
+        .and_then(|ext| EXTENSIONS.iter().find(|(known, _, _)| *known == ext));
+
+    Some(match known {
+        Some((_, declared, sniffed_as)) if *sniffed_as == detected => ContentTypeInfo {
+            content_type: declared.to_string(),
+            extension_mismatch: false,
+        },
+        Some(_) => ContentTypeInfo {
+            content_type: detected.to_string(),
+            extension_mismatch: true,
+        },
+        None => ContentTypeInfo {
+            content_type: detected.to_string(),
+            extension_mismatch: false,
+        },
+    })
+}
+
+/// Detect a content type from leading bytes alone
+pub fn detect_content_type(head: &[u8]) -> Option<&'static str> {
+    if head.is_empty() {
+        return None;
+    }
+
+    if let Some((_, content_type)) = SIGNATURES.iter().find(|(magic, _)| head.starts_with(magic)) {
+        return Some(content_type);
+    }
+
+    if head.len() >= 12 && &head[..4] == b"RIFF" {
+        match &head[8..12] {
+            b"WEBP" => return Some("image/webp"),
+            b"WAVE" => return Some("audio/wav"),
+            _ => {}
+        }
+    }
+
+    if head.len() >= 12 && &head[4..8] == b"ftyp" {
+        return Some(match &head[8..12] {
+            b"heic" | b"heix" | b"mif1" => "image/heic",
+            b"qt  " => "video/quicktime",
+            _ => "video/mp4",
+        });
+    }
+
+    Some(if looks_like_text(head) {
+        "text/plain"
+    } else {
+        UNKNOWN_CONTENT_TYPE
+    })
+}
+
+/// UTF-8 without NUL bytes; a character cut off by the read limit is allowed
+fn looks_like_text(head: &[u8]) -> bool {
+    if head.contains(&0) {
+        return false;
+    }
+    match std::str::from_utf8(head) {
+        Ok(_) => true,
+        Err(e) => e.error_len().is_none() && head.len() >= CONTENT_SNIFF_LIMIT,
+    }
+}
+
+/// Top-level part of a content type (`image` for `image/png`)
+pub fn top_level_type(content_type: &str) -> &str {
+    content_type
+        .split_once('/')
+        .map_or(content_type, |(top, _)| top)
+}
+
+/// Whether a content type matches a pattern like `image/*` or `application/pdf`
+pub fn content_type_matches(pattern: &str, content_type: &str) -> bool {
+    let pattern = pattern.trim();
+    match pattern.strip_suffix("/*") {
+        Some(top) => top_level_type(content_type).eq_ignore_ascii_case(top),
+        None => pattern == "*/*" || pattern.eq_ignore_ascii_case(content_type),
+    }
+}
+
+/// Which archive entries a restore writes
+///
+/// An entry is restored when it lies under one of `paths` or its content
+/// matches one of `content_types`. An empty filter restores everything.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+pub struct RestoreFilter {
+    /// Archive paths, or folders whose contents are restored
+    #[serde(default)]
+    pub paths: Vec<String>,
+    /// Content types such as `image/*` or `application/pdf`
+    #[serde(default)]
+    pub content_types: Vec<String>,
+}
+
+impl RestoreFilter {
+    pub fn is_empty(&self) -> bool {
+        self.paths.is_empty() && self.content_types.is_empty()
+    }
+
+    /// Whether the filter needs entry content to decide
+    pub fn filters_by_type(&self) -> bool {
+        !self.content_types.is_empty()
+    }
+
+    /// Decide whether an entry is restored
+    ///
+    /// An entry whose type couldn't be detected only matches by path.
+    pub fn includes(&self, entry_path: &Path, content_type: Option<&str>) -> bool {
+        if self.is_empty() {
+            return true;
+        }
+        let by_path = self
+            .paths
+            .iter()
+            .any(|selected| path_is_within(entry_path, Path::new(selected)));
+        let by_type = content_type.is_some_and(|content_type| {
+            self.content_types
+                .iter()
+                .any(|pattern| content_type_matches(pattern, content_type))
+        });
+        by_path || by_type
+    }
+}
+
+fn path_is_within(entry_path: &Path, selected: &Path) -> bool {
+    let normal = |path: &Path| -> Vec<String> {
+        path.components()
+            .filter_map(|component| match component {
+                Component::Normal(part) => Some(part.to_string_lossy().into_owned()),
+                _ => None,
+            })
+            .collect()
+    };
+    let selected = normal(selected);
+    !selected.is_empty() && normal(entry_path).starts_with(&selected)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    const PNG_BYTES: &[u8] = b"\x89PNG\r\n\x1a\n\x00\x00\x00\rIHDR";
+    const PDF_BYTES: &[u8] = b"%PDF-1.7\n%\xE2\xE3\xCF\xD3\n";
+
+    fn fixture(dir: &TempDir, name: &str, content: &[u8]) -> std::path::PathBuf {
+        let path = dir.path().join(name);
+        std::fs::write(&path, content).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_honest_extensions_use_declared_type() {
+        let dir = TempDir::new().unwrap();
+        let png = sniff_content_type(&fixture(&dir, "photo.png", PNG_BYTES)).unwrap();
+        let csv = sniff_content_type(&fixture(&dir, "data.csv", b"a,b\n1,2\n")).unwrap();
+
+        assert_eq!(png.content_type, "image/png");
+        assert!(!png.extension_mismatch);
+        assert_eq!(csv.content_type, "text/csv");
+        assert!(!csv.extension_mismatch);
+    }
+
+    #[test]
+    fn test_lying_extensions_are_flagged() {
+        let dir = TempDir::new().unwrap();
+        let pdf_as_jpg = sniff_content_type(&fixture(&dir, "scan.jpg", PDF_BYTES)).unwrap();
+        let png_as_txt = sniff_content_type(&fixture(&dir, "notes.txt", PNG_BYTES)).unwrap();
+
+        assert_eq!(pdf_as_jpg.content_type, "application/pdf");
+        assert!(pdf_as_jpg.extension_mismatch);
+        assert_eq!(png_as_txt.content_type, "image/png");
+        assert!(png_as_txt.extension_mismatch);
+    }
+
+    #[test]
+    fn test_office_documents_are_zip_containers() {
+        let info = classify_content(Path::new("report.docx"), b"PK\x03\x04rest").unwrap();
+        assert!(info.content_type.contains("wordprocessingml"));
+        assert!(!info.extension_mismatch);
+
+        let info = classify_content(Path::new("archive.zip"), PDF_BYTES).unwrap();
+        assert!(info.extension_mismatch);
+    }
+
+    #[test]
+    fn test_unknown_extension_is_never_a_mismatch() {
+        let info = classify_content(Path::new("blob.bin"), &[0, 1, 2, 3]).unwrap();
+        assert_eq!(info.content_type, UNKNOWN_CONTENT_TYPE);
+        assert!(!info.extension_mismatch);
+    }
+
+    #[test]
+    fn test_empty_and_unreadable_files_have_no_type() {
+        let dir = TempDir::new().unwrap();
+        assert!(sniff_content_type(&fixture(&dir, "empty.txt", b"")).is_none());
+        assert!(sniff_content_type(&dir.path().join("missing.pdf")).is_none());
+    }
+
+    #[test]
+    fn test_sniffing_reads_a_bounded_prefix() {
+        let dir = TempDir::new().unwrap();
+        let mut content = vec![b'a'; CONTENT_SNIFF_LIMIT];
+        content.extend_from_slice(&[0; 64]);
+        let info = sniff_content_type(&fixture(&dir, "long.txt", &content)).unwrap();
+        assert_eq!(info.content_type, "text/plain");
+    }
+
+    #[test]
+    fn test_type_patterns() {
+        assert!(content_type_matches("image/*", "image/png"));
+        assert!(content_type_matches("application/pdf", "application/pdf"));
+        assert!(!content_type_matches("image/*", "application/pdf"));
+        assert!(!content_type_matches("application/pdf", "application/zip"));
+        assert_eq!(top_level_type("video/mp4"), "video");
+    }
+
+    #[test]
+    fn test_restore_filter_matches_paths_or_types() {
+        let filter = RestoreFilter {
+            paths: vec!["docs/tax".to_string()],
+            content_types: vec!["image/*".to_string()],
+        };
+
+        assert!(filter.includes(Path::new("docs/tax/2024.pdf"), Some("application/pdf")));
+        assert!(filter.includes(Path::new("photos/a.jpg"), Some("image/jpeg")));
+        assert!(!filter.includes(Path::new("docs/taxes.pdf"), Some("application/pdf")));
+        assert!(!filter.includes(Path::new("photos/a.jpg"), None));
+        assert!(RestoreFilter::default().includes(Path::new("any"), None));
+    }
+}
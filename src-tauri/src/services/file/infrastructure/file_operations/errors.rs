@@ -1,5 +1,7 @@
 //! Error types for file operations module
 
+use super::validation::SafetyLimit;
+use super::validation::path_limits::{PathLimitViolation, summarize_path_violations};
 use crate::constants::*;
 use std::path::PathBuf;
 use thiserror::Error;
@@ -78,6 +80,34 @@ pub enum FileOpsError {
     /// Cross-platform path error
     #[error("Cross-platform path error: {message}")]
     CrossPlatformPathError { message: String },
+
+    /// Archive entries exceed the platform's path length limits
+    #[error(
+        "{} archive entries exceed platform path limits: {}",
+        .violations.len(),
+        summarize_path_violations(.violations)
+    )]
+    PathLimitExceeded { violations: Vec<PathLimitViolation> },
+
+    /// The archive is past one of its safety limits
+    #[error("Archive exceeds safety limits: {which} is {observed} (limit {limit})")]
+    ArchiveExceedsSafetyLimits {
+        which: SafetyLimit,
+        observed: u64,
+        limit: u64,
+    },
+
+    /// The selection contains, or is inside, storage the app writes to
+    #[error("Selection overlaps app storage: {}", summarize_paths(.paths))]
+    SelectionOverlapsAppData { paths: Vec<PathBuf> },
+}
+
+fn summarize_paths(paths: &[PathBuf]) -> String {
+    paths
+        .iter()
+        .map(|path| path.display().to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
 }
 
 impl From<std::io::Error> for FileOpsError {
@@ -130,6 +160,27 @@ pub fn user_message(&self) -> String {
             FileOpsError::SymlinkDetected { path } => {
                 format!("Security risk: Symlink detected at {}", path.display())
             }
+            FileOpsError::PathLimitExceeded { violations } => {
+                format!(
+                    "{} file(s) have paths too long for this system. Choose a shorter output folder or allow long names to be shortened.",
+                    violations.len()
+                )
+            }
+            FileOpsError::ArchiveExceedsSafetyLimits {
+                which,
+                observed,
+                limit,
+            } => {
+                format!(
+                    "This archive's {which} ({observed}) is over the safe limit of {limit}, so nothing was written."
+                )
+            }
+            FileOpsError::SelectionOverlapsAppData { paths } => {
+                format!(
+                    "The selection includes Barqly Vault's own storage ({}). Select a folder that doesn't contain it.",
+                    summarize_paths(paths)
+                )
+            }
             _ => self.to_string(),
         }
     }
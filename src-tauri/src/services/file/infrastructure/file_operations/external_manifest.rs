@@ -5,6 +5,7 @@
 
 use super::{ArchiveOperation, FileInfo};
 use super::{FileOpsError, Result};
+use crate::types::format_byte_size;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::fs;
@@ -68,7 +69,7 @@ pub fn new(
                 .map(|n| n.to_string_lossy().to_string())
                 .unwrap_or_else(|| "encrypted.age".to_string()),
             total_files: files.len(),
-            vault_size: format_file_size(encrypted_metadata.len()),
+            vault_size: format_byte_size(encrypted_metadata.len()),
         };
 
         let contents: Vec<ContentEntry> = files
@@ -84,7 +85,7 @@ pub fn new(
 
                 ContentEntry {
                     file: relative_path,
-                    size: format_file_size(file.size),
+                    size: format_byte_size(file.size),
                     hash: file.hash.clone(),
                 }
             })
@@ -180,45 +181,11 @@ pub fn generate_external_manifest_path(encrypted_file_path: &Path) -> PathBuf {
     encrypted_file_path.with_extension("manifest")
 }
 
-/// Format file size in human-readable format
-fn format_file_size(bytes: u64) -> String {
-    if bytes == 0 {
-        return "0 B".to_string();
-    }
-
-    const UNITS: &[&str] = &["B", "KB", "MB", "GB", "TB"];
-    const THRESHOLD: f64 = 1024.0;
-
-    let mut size = bytes as f64;
-    let mut unit_index = 0;
-
-    while size >= THRESHOLD && unit_index < UNITS.len() - 1 {
-        size /= THRESHOLD;
-        unit_index += 1;
-    }
-
-    if unit_index == 0 {
-        format!("{} {}", bytes, UNITS[unit_index])
-    } else {
-        format!("{:.1} {}", size, UNITS[unit_index])
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
     use std::path::PathBuf;
 
-    #[test]
-    fn test_format_file_size() {
-        assert_eq!(format_file_size(0), "0 B");
-        assert_eq!(format_file_size(512), "512 B");
-        assert_eq!(format_file_size(1024), "1.0 KB");
-        assert_eq!(format_file_size(1536), "1.5 KB");
-        assert_eq!(format_file_size(1048576), "1.0 MB");
-        assert_eq!(format_file_size(2621440), "2.5 MB");
-    }
-
     #[test]
     fn test_generate_external_manifest_path() {
         let encrypted_path = PathBuf::from("/path/to/vault.age");
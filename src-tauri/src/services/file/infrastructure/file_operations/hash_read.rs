@@ -0,0 +1,324 @@
+//! File reads for whole-file digests
+//!
+//! Manifest hashes, archive hashes and upload checksums all read their file
+//! through `read_for_hash`. On 64-bit targets a file of at least
+//! `MAP_THRESHOLD` bytes is memory-mapped and handed to the hasher in
+//! `MAP_WINDOW` slices, which saves the copy into a read buffer and lets the
+//! kernel read ahead. Smaller files, 32-bit targets and files that can't be
+//! mapped (some network and FUSE filesystems) are read with a buffer.
+//!
+//! A mapped file that changes while it is hashed would give a digest of
+//! neither version, and touching pages past a truncated end faults the
+//! process. The size and modification time are checked before every window
+//! and after the last; on any change the partial digest is dropped and the
+//! file is read again with the buffer. The check narrows the truncation race
+//! to a single window rather than closing it.
+
+use super::{FileOpsError, Result};
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::SystemTime;
+use tracing::debug;
+
+/// Smallest file that is mapped; below this a buffered read is as fast
+pub const MAP_THRESHOLD: u64 = 1024 * 1024;
+
+/// Bytes hashed per mapped slice, between change checks
+pub const MAP_WINDOW: usize = 16 * 1024 * 1024;
+
+/// Buffer for buffered reads (1 MiB, heap allocated)
+const READ_BUFFER_SIZE: usize = 1024 * 1024;
+
+static MAPPED: AtomicU64 = AtomicU64::new(0);
+static BUFFERED: AtomicU64 = AtomicU64::new(0);
+static FALLBACKS: AtomicU64 = AtomicU64::new(0);
+
+/// How a file was read for hashing
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HashReadPath {
+    Mapped,
+    Buffered,
+}
+
+/// Files hashed, by how they were read
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+pub struct HashReadCounts {
+    /// Hashed through a memory map
+    pub mapped: u64,
+    /// Read with a buffer
+    pub buffered: u64,
+    /// Mapped reads given up for a buffered one (map failed or file changed)
+    pub fallbacks: u64,
+}
+
+impl HashReadCounts {
+    /// Counts for the whole process so far
+    pub fn current() -> Self {
+        Self {
+            mapped: MAPPED.load(Ordering::Relaxed),
+            buffered: BUFFERED.load(Ordering::Relaxed),
+            fallbacks: FALLBACKS.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Reads counted since `earlier` was taken
+    pub fn since(self, earlier: Self) -> Self {
+        Self {
+            mapped: self.mapped.saturating_sub(earlier.mapped),
+            buffered: self.buffered.saturating_sub(earlier.buffered),
+            fallbacks: self.fallbacks.saturating_sub(earlier.fallbacks),
+        }
+    }
+}
+
+/// Feed a whole file to a hasher state, returning it and how the file was read
+///
+/// `init` makes a fresh state. It is called again when a mapped read is given
+/// up, so nothing from the abandoned attempt reaches the digest.
+pub fn read_for_hash<S>(
+    path: &Path,
+    init: impl Fn() -> S,
+    mut update: impl FnMut(&mut S, &[u8]),
+) -> Result<(S, HashReadPath)> {
+    read_for_hash_in(path, MAP_WINDOW, &init, &mut update)
+}
+
+fn read_for_hash_in<S>(
+    path: &Path,
+    window: usize,
+    init: &impl Fn() -> S,
+    update: &mut impl FnMut(&mut S, &[u8]),
+) -> Result<(S, HashReadPath)> {
+    let file = File::open(path).map_err(|_| FileOpsError::FileNotFound {
+        path: path.to_path_buf(),
+    })?;
+
+    #[cfg(target_pointer_width = "64")]
+    match read_mapped(&file, window, init, update) {
+        Ok(Mapped::Done(state)) => {
+            MAPPED.fetch_add(1, Ordering::Relaxed);
+            return Ok((state, HashReadPath::Mapped));
+        }
+        Ok(Mapped::TooSmall) => {}
+        Ok(Mapped::Changed) => {
+            FALLBACKS.fetch_add(1, Ordering::Relaxed);
+            debug!(path = %path.display(), "File changed while mapped, re-reading with a buffer");
+        }
+        Err(e) => {
+            FALLBACKS.fetch_add(1, Ordering::Relaxed);
+            debug!(path = %path.display(), error = %e, "Couldn't map file, reading with a buffer");
+        }
+    }
+    #[cfg(not(target_pointer_width = "64"))]
+    let _ = window;
+
+    let state =
+        read_buffered(&file, init, update).map_err(|e| FileOpsError::HashCalculationFailed {
+            message: format!("Failed to read file: {e}"),
+        })?;
+    BUFFERED.fetch_add(1, Ordering::Relaxed);
+    Ok((state, HashReadPath::Buffered))
+}
+
+/// Size and modification time, compared to spot a file changing under a map
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Fingerprint {
+    len: u64,
+    modified: Option<SystemTime>,
+}
+
+impl Fingerprint {
+    fn of(file: &File) -> io::Result<Self> {
+        let metadata = file.metadata()?;
+        Ok(Self {
+            len: metadata.len(),
+            modified: metadata.modified().ok(),
+        })
+    }
+}
+
+#[cfg(target_pointer_width = "64")]
+enum Mapped<S> {
+    Done(S),
+    TooSmall,
+    Changed,
+}
+
+#[cfg(target_pointer_width = "64")]
+fn read_mapped<S>(
+    file: &File,
+    window: usize,
+    init: &impl Fn() -> S,
+    update: &mut impl FnMut(&mut S, &[u8]),
+) -> io::Result<Mapped<S>> {
+    let before = Fingerprint::of(file)?;
+    if before.len < MAP_THRESHOLD {
+        return Ok(Mapped::TooSmall);
+    }
+
+    // The map is only read, and each window only after the file is seen
+    // unchanged; see the module docs for the race that remains
+    let map = unsafe { memmap2::Mmap::map(file)? };
+    if map.len() as u64 != before.len {
+        return Ok(Mapped::Changed);
+    }
+    // Hints only; the read is correct without them
+    #[cfg(unix)]
+    let _ = map.advise(memmap2::Advice::Sequential);
+
+    let mut state = init();
+    for (index, slice) in map.chunks(window).enumerate() {
+        if Fingerprint::of(file)? != before {
+            return Ok(Mapped::Changed);
+        }
+        #[cfg(unix)]
+        {
+            let next = (index + 1) * window;
+            if next < map.len() {
+                let len = window.min(map.len() - next);
+                let _ = map.advise_range(memmap2::Advice::WillNeed, next, len);
+            }
+        }
+        #[cfg(not(unix))]
+        let _ = index;
+        update(&mut state, slice);
+    }
+
+    if Fingerprint::of(file)? != before {
+        return Ok(Mapped::Changed);
+    }
+    Ok(Mapped::Done(state))
+}
+
+fn read_buffered<S>(
+    mut file: &File,
+    init: &impl Fn() -> S,
+    update: &mut impl FnMut(&mut S, &[u8]),
+) -> io::Result<S> {
+    let mut state = init();
+    let mut buffer = vec![0u8; READ_BUFFER_SIZE];
+    loop {
+        let n = file.read(&mut buffer)?;
+        if n == 0 {
+            return Ok(state);
+        }
+        update(&mut state, &buffer[..n]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sha2::{Digest, Sha256};
+    use std::io::{Seek, SeekFrom, Write};
+    use tempfile::TempDir;
+
+    fn patterned(len: usize) -> Vec<u8> {
+        (0..len).map(|i| (i * 31 % 251) as u8).collect()
+    }
+
+    fn sha256_in(path: &Path, window: usize) -> (String, HashReadPath) {
+        let (hasher, read) =
+            read_for_hash_in(path, window, &Sha256::new, &mut |h: &mut Sha256, b| {
+                h.update(b)
+            })
+            .unwrap();
+        (hex::encode(hasher.finalize()), read)
+    }
+
+    fn sha256_buffered(path: &Path) -> String {
+        let file = File::open(path).unwrap();
+        let hasher =
+            read_buffered(&file, &Sha256::new, &mut |h: &mut Sha256, b| h.update(b)).unwrap();
+        hex::encode(hasher.finalize())
+    }
+
+    #[test]
+    fn test_mapped_and_buffered_digests_match() {
+        let temp = TempDir::new().unwrap();
+        let threshold = MAP_THRESHOLD as usize;
+
+        for len in [0, 100, threshold - 1, threshold, 3 * threshold + 17] {
+            let path = temp.path().join(format!("fixture-{len}.bin"));
+            let content = patterned(len);
+            std::fs::write(&path, &content).unwrap();
+
+            // Small windows so the mapped read crosses many boundaries
+            let (digest, read) = sha256_in(&path, 64 * 1024);
+            assert_eq!(digest, sha256_buffered(&path), "len {len}");
+            assert_eq!(digest, hex::encode(Sha256::digest(&content)), "len {len}");
+
+            let expected = if cfg!(target_pointer_width = "64") && len >= threshold {
+                HashReadPath::Mapped
+            } else {
+                HashReadPath::Buffered
+            };
+            assert_eq!(read, expected, "len {len}");
+        }
+    }
+
+    #[cfg(target_pointer_width = "64")]
+    #[test]
+    fn test_truncation_mid_hash_restarts_buffered() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("shrinking.bin");
+        let content = patterned(4 * 1024 * 1024);
+        std::fs::write(&path, &content).unwrap();
+        let kept = 2 * 1024 * 1024 + 512 * 1024;
+
+        let before = HashReadCounts::current();
+        let mut truncated = false;
+        let (hasher, read) = read_for_hash_in(
+            &path,
+            1024 * 1024,
+            &Sha256::new,
+            &mut |h: &mut Sha256, bytes| {
+                h.update(bytes);
+                // Another process shortens the file after the first window
+                if !truncated {
+                    truncated = true;
+                    File::options()
+                        .write(true)
+                        .open(&path)
+                        .unwrap()
+                        .set_len(kept as u64)
+                        .unwrap();
+                }
+            },
+        )
+        .unwrap();
+
+        assert_eq!(read, HashReadPath::Buffered);
+        assert_eq!(
+            hex::encode(hasher.finalize()),
+            hex::encode(Sha256::digest(&content[..kept]))
+        );
+        assert!(HashReadCounts::current().since(before).fallbacks >= 1);
+    }
+
+    #[test]
+    fn test_large_sparse_file() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("sparse.bin");
+        let len = 2 * MAP_WINDOW as u64 + 8 * 1024 * 1024 + 5;
+
+        let mut file = File::create(&path).unwrap();
+        file.set_len(len).unwrap();
+        file.write_all(b"head").unwrap();
+        file.seek(SeekFrom::Start(MAP_WINDOW as u64 - 2)).unwrap();
+        file.write_all(b"across").unwrap();
+        file.seek(SeekFrom::Start(len - 4)).unwrap();
+        file.write_all(b"tail").unwrap();
+        drop(file);
+
+        let (hasher, read) =
+            read_for_hash(&path, Sha256::new, |h: &mut Sha256, b| h.update(b)).unwrap();
+        assert_eq!(hex::encode(hasher.finalize()), sha256_buffered(&path));
+        if cfg!(target_pointer_width = "64") {
+            assert_eq!(read, HashReadPath::Mapped);
+        }
+    }
+}
@@ -0,0 +1,383 @@
+//! Tolerance for source files that can't be read during encryption
+//!
+//! Files held open by other applications (mail stores, databases, files
+//! locked on Windows) fail to open or read. Depending on the
+//! `LockedFilePolicy` they either abort the operation or are skipped and
+//! reported, so the user knows the backup is incomplete.
+//!
+//! SQLite databases in WAL mode are only consistent together with their
+//! `-wal` and `-shm` companions, so if any file of that trio is skipped the
+//! others are skipped with it rather than capturing a torn database.
+
+use super::{FileOpsError, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tracing::warn;
+
+/// Suffixes of SQLite companion files that must travel with their database
+const SQLITE_COMPANION_SUFFIXES: [&str; 2] = ["-wal", "-shm"];
+
+/// What to do when a source file can't be read
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+pub enum LockedFilePolicy {
+    /// Abort the whole operation (previous behavior)
+    #[default]
+    Fail,
+    /// Skip the file and record it
+    Skip,
+    /// Retry a few times before skipping (`attempts` includes the first try)
+    RetryThenSkip { attempts: u32, delay_ms: u64 },
+}
+
+/// A source file left out of the archive
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+pub struct SkippedEntry {
+    /// Path relative to the selection, as it would appear in the manifest
+    pub path: String,
+    pub reason: String,
+}
+
+/// A skipped entry together with the source file it came from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SkippedFile {
+    pub source_path: PathBuf,
+    pub entry: SkippedEntry,
+}
+
+/// Result of reading a file under a `LockedFilePolicy`
+#[derive(Debug)]
+pub enum ReadOutcome<T> {
+    Read(T),
+    /// Unreadable and skipped; carries the reason
+    Skipped(String),
+}
+
+/// Run `read` under `policy`
+///
+/// With `Fail` the error is returned unchanged; otherwise the last error
+/// becomes the skip reason.
+pub fn read_with_policy<T>(
+    policy: LockedFilePolicy,
+    mut read: impl FnMut() -> Result<T>,
+) -> Result<ReadOutcome<T>> {
+    let (attempts, delay) = match policy {
+        LockedFilePolicy::Fail => return read().map(ReadOutcome::Read),
+        LockedFilePolicy::Skip => (1, Duration::ZERO),
+        LockedFilePolicy::RetryThenSkip { attempts, delay_ms } => {
+            (attempts.max(1), Duration::from_millis(delay_ms))
+        }
+    };
+
+    let mut last_error: Option<FileOpsError> = None;
+    for attempt in 1..=attempts {
+        if attempt > 1 {
+            std::thread::sleep(delay);
+        }
+        match read() {
+            Ok(value) => return Ok(ReadOutcome::Read(value)),
+            Err(e) => last_error = Some(e),
+        }
+    }
+
+    Ok(ReadOutcome::Skipped(
+        last_error.map(|e| e.to_string()).unwrap_or_default(),
+    ))
+}
+
+/// Database path for a SQLite `-wal`/`-shm` companion file
+pub fn sqlite_database_for(path: &Path) -> Option<PathBuf> {
+    let name = path.file_name()?.to_str()?;
+    SQLITE_COMPANION_SUFFIXES.iter().find_map(|suffix| {
+        name.strip_suffix(suffix)
+            .filter(|base| !base.is_empty())
+            .map(|base| path.with_file_name(base))
+    })
+}
+
+/// Skip the rest of any SQLite trio that lost a member
+///
+/// `included` holds source paths still in the archive; returns the paths
+/// that must now be skipped, each with its reason.
+pub fn torn_sqlite_members(included: &[PathBuf], skipped: &[PathBuf]) -> Vec<(PathBuf, String)> {
+    let group_of = |path: &Path| sqlite_database_for(path).unwrap_or_else(|| path.to_path_buf());
+
+    // Only groups that actually contain a companion file are SQLite trios
+    let companion_groups: HashSet<PathBuf> = included
+        .iter()
+        .chain(skipped)
+        .filter_map(|path| sqlite_database_for(path))
+        .collect();
+
+    let torn: Vec<(PathBuf, &PathBuf)> = skipped
+        .iter()
+        .map(|path| (group_of(path), path))
+        .filter(|(group, _)| companion_groups.contains(group))
+        .collect();
+
+    included
+        .iter()
+        .filter_map(|path| {
+            let group = group_of(path);
+            let (_, cause) = torn.iter().find(|(torn_group, _)| *torn_group == group)?;
+            let cause_name = cause
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default();
+            warn!(
+                path = %path.display(),
+                skipped = %cause_name,
+                "Skipping SQLite file to avoid capturing a torn database"
+            );
+            Some((
+                path.clone(),
+                format!("SQLite database skipped together with {cause_name}"),
+            ))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    fn io_error() -> FileOpsError {
+        FileOpsError::IoError {
+            message: "file is locked".to_string(),
+            source: std::io::Error::other("locked"),
+        }
+    }
+
+    #[test]
+    fn test_fail_policy_propagates_error() {
+        let result = read_with_policy(LockedFilePolicy::Fail, || -> Result<()> { Err(io_error()) });
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_skip_policy_records_reason() {
+        let outcome =
+            read_with_policy(LockedFilePolicy::Skip, || -> Result<()> { Err(io_error()) }).unwrap();
+        match outcome {
+            ReadOutcome::Skipped(reason) => assert!(reason.contains("file is locked")),
+            ReadOutcome::Read(_) => panic!("expected skip"),
+        }
+    }
+
+    #[test]
+    fn test_retry_then_skip_succeeds_after_transient_lock() {
+        let calls = Cell::new(0);
+        let policy = LockedFilePolicy::RetryThenSkip {
+            attempts: 3,
+            delay_ms: 0,
+        };
+
+        let outcome = read_with_policy(policy, || {
+            calls.set(calls.get() + 1);
+            if calls.get() < 3 {
+                Err(io_error())
+            } else {
+                Ok(42)
+            }
+        })
+        .unwrap();
+
+        assert!(matches!(outcome, ReadOutcome::Read(42)));
+        assert_eq!(calls.get(), 3);
+    }
+
+    #[test]
+    fn test_retry_then_skip_gives_up() {
+        let calls = Cell::new(0);
+        let policy = LockedFilePolicy::RetryThenSkip {
+            attempts: 2,
+            delay_ms: 0,
+        };
+
+        let outcome = read_with_policy(policy, || -> Result<()> {
+            calls.set(calls.get() + 1);
+            Err(io_error())
+        })
+        .unwrap();
+
+        assert!(matches!(outcome, ReadOutcome::Skipped(_)));
+        assert_eq!(calls.get(), 2);
+    }
+
+    #[test]
+    fn test_sqlite_database_for_companions() {
+        assert_eq!(
+            sqlite_database_for(Path::new("data/app.db-wal")),
+            Some(PathBuf::from("data/app.db"))
+        );
+        assert_eq!(
+            sqlite_database_for(Path::new("app.sqlite-shm")),
+            Some(PathBuf::from("app.sqlite"))
+        );
+        assert_eq!(sqlite_database_for(Path::new("app.db")), None);
+        assert_eq!(sqlite_database_for(Path::new("-wal")), None);
+    }
+
+    #[test]
+    fn test_locked_wal_skips_whole_trio() {
+        let included = vec![
+            PathBuf::from("data/app.db"),
+            PathBuf::from("data/app.db-shm"),
+            PathBuf::from("data/notes.txt"),
+        ];
+        let skipped = vec![PathBuf::from("data/app.db-wal")];
+
+        let torn = torn_sqlite_members(&included, &skipped);
+        let paths: Vec<&PathBuf> = torn.iter().map(|(p, _)| p).collect();
+
+        assert_eq!(paths, vec![&included[0], &included[1]]);
+        assert!(torn[0].1.contains("app.db-wal"));
+    }
+
+    #[test]
+    fn test_locked_database_skips_companions() {
+        let included = vec![
+            PathBuf::from("app.db-wal"),
+            PathBuf::from("app.db-shm"),
+            PathBuf::from("other.db"),
+        ];
+        let skipped = vec![PathBuf::from("app.db")];
+
+        let torn = torn_sqlite_members(&included, &skipped);
+        assert_eq!(torn.len(), 2);
+    }
+
+    #[test]
+    fn test_plain_skipped_file_affects_nothing_else() {
+        let included = vec![PathBuf::from("report.pdf"), PathBuf::from("app.db")];
+        let skipped = vec![PathBuf::from("mail.pst")];
+
+        assert!(torn_sqlite_members(&included, &skipped).is_empty());
+    }
+
+    fn folder_selection(dir: &Path) -> Vec<String> {
+        vec![dir.to_string_lossy().to_string()]
+    }
+
+    /// Make `path` unreadable the way another process would on this platform
+    ///
+    /// Returns a guard keeping the lock alive, or `None` when the platform
+    /// can't simulate it (e.g. running as root on Unix).
+    #[cfg(unix)]
+    fn lock_file(path: &Path) -> Option<()> {
+        use std::os::unix::fs::PermissionsExt;
+        if nix::unistd::geteuid().is_root() {
+            return None;
+        }
+        std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o000)).unwrap();
+        Some(())
+    }
+
+    #[cfg(windows)]
+    fn lock_file(path: &Path) -> Option<std::fs::File> {
+        use std::os::windows::fs::OpenOptionsExt;
+        // No sharing: any other open fails with a sharing violation
+        std::fs::OpenOptions::new()
+            .read(true)
+            .share_mode(0)
+            .open(path)
+            .ok()
+    }
+
+    #[cfg(any(unix, windows))]
+    #[test]
+    fn test_locked_file_fails_by_default() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let locked = dir.path().join("mail.pst");
+        std::fs::write(&locked, b"mailbox").unwrap();
+        std::fs::write(dir.path().join("notes.txt"), b"notes").unwrap();
+        let Some(_lock) = lock_file(&locked) else {
+            return;
+        };
+
+        let result = super::super::collect_files_with_policy(
+            &folder_selection(dir.path()),
+            super::super::SelectionType::Folder,
+            None,
+            LockedFilePolicy::Fail,
+            &[],
+        );
+
+        assert!(result.is_err());
+    }
+
+    #[cfg(any(unix, windows))]
+    #[test]
+    fn test_locked_file_skipped_and_recorded() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let locked = dir.path().join("mail.pst");
+        std::fs::write(&locked, b"mailbox").unwrap();
+        std::fs::write(dir.path().join("notes.txt"), b"notes").unwrap();
+        let Some(_lock) = lock_file(&locked) else {
+            return;
+        };
+
+        let collection = super::super::collect_files_with_policy(
+            &folder_selection(dir.path()),
+            super::super::SelectionType::Folder,
+            None,
+            LockedFilePolicy::RetryThenSkip {
+                attempts: 2,
+                delay_ms: 1,
+            },
+            &[],
+        )
+        .unwrap();
+
+        let names: Vec<&str> = collection
+            .files
+            .iter()
+            .map(|f| f.relative_path.as_str())
+            .collect();
+        assert_eq!(names, vec!["notes.txt"]);
+        assert_eq!(collection.skipped.len(), 1);
+        assert_eq!(collection.skipped[0].entry.path, "mail.pst");
+        assert_eq!(collection.skipped[0].source_path, locked);
+        assert!(!collection.skipped[0].entry.reason.is_empty());
+    }
+
+    #[cfg(any(unix, windows))]
+    #[test]
+    fn test_locked_wal_skips_database_atomically() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::write(dir.path().join("app.db"), b"db").unwrap();
+        std::fs::write(dir.path().join("app.db-shm"), b"shm").unwrap();
+        let wal = dir.path().join("app.db-wal");
+        std::fs::write(&wal, b"wal").unwrap();
+        std::fs::write(dir.path().join("notes.txt"), b"notes").unwrap();
+        let Some(_lock) = lock_file(&wal) else {
+            return;
+        };
+
+        let collection = super::super::collect_files_with_policy(
+            &folder_selection(dir.path()),
+            super::super::SelectionType::Folder,
+            None,
+            LockedFilePolicy::Skip,
+            &[],
+        )
+        .unwrap();
+
+        let names: Vec<&str> = collection
+            .files
+            .iter()
+            .map(|f| f.relative_path.as_str())
+            .collect();
+        assert_eq!(names, vec!["notes.txt"]);
+
+        let mut skipped: Vec<&str> = collection
+            .skipped
+            .iter()
+            .map(|s| s.entry.path.as_str())
+            .collect();
+        skipped.sort();
+        assert_eq!(skipped, vec!["app.db", "app.db-shm", "app.db-wal"]);
+    }
+}
@@ -21,29 +21,77 @@
 
 pub mod archive_manifest;
 pub mod archive_operations;
+pub mod content_type;
 pub mod errors;
 pub mod external_manifest;
+pub mod hash_read;
+pub mod locked_files;
+pub mod ownership;
+pub mod parity;
+pub mod raw_path;
+pub mod resilient_source;
 pub mod selection;
 pub mod staging;
+pub mod transform;
+pub mod upload_metadata;
 pub mod utils;
 pub mod validation;
 
 use crate::constants::*;
+use crate::services::shared::infrastructure::io::IoPacer;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
+use std::sync::Arc;
 
-pub use archive_manifest::{Manifest, verify_manifest};
-pub use archive_operations::{create_archive, create_archive_with_file_info, extract_archive};
+pub use archive_manifest::{
+    DEFAULT_VERIFY_IGNORE, Manifest, UnexpectedEntries, UnexpectedEntry, VerificationStrictness,
+    find_unexpected_entries, verify_directories, verify_listed_files, verify_manifest,
+    verify_manifest_file,
+};
+pub use archive_operations::{
+    EmbeddedManifest, EntryPreview, ExtractionResult, PathMapping, PreviewContent, PreviewLimits,
+    PreviewOmission, create_archive, create_archive_with_file_info, extract_archive,
+    extract_archive_with_report, preview_entries, read_embedded_manifest,
+};
+pub use archive_operations::{ExtractionJournal, ExtractionPolicy, RESUME_JOURNAL_SUFFIX};
+pub use archive_operations::{PARTIAL_FILE_SUFFIX, SalvagedEntry, TarSalvage, salvage_tar_gz};
+pub use content_type::{
+    ContentTypeInfo, RestoreFilter, content_type_matches, sniff_content_type, top_level_type,
+};
 pub use errors::FileOpsError;
 pub use external_manifest::{
     ExternalManifest, create_external_manifest_for_archive, generate_external_manifest_path,
 };
+pub use hash_read::{HashReadCounts, HashReadPath};
+pub use locked_files::{LockedFilePolicy, SkippedEntry, SkippedFile};
+pub use ownership::{
+    FidelityReport, FileOwnership, OwnershipFallback, OwnershipFallbackReason, OwnershipPolicy,
+    SystemUserDatabase, UserDatabase, apply_ownership, record_ownership, resolve_ownership,
+};
+pub use parity::{
+    PARITY_BLOCK_SIZE, PARITY_STRIPE_BLOCKS, ParitySidecar, generate_parity, generate_parity_path,
+};
+pub use raw_path::{RawPath, archive_entry_name, is_lossy_name, restore_true_names};
+pub use resilient_source::{
+    FsSourceReader, ResilientSource, ResilientSourceConfig, ResilientSourceReport, SourceReader,
+};
 pub use selection::{FileSelection, SelectionType};
-pub use staging::StagingArea;
-pub use utils::{CollectedFile, collect_files_with_metadata, read_archive_with_size_check};
+pub use staging::{STAGING_DIR, StagingArea, is_live_staging};
+pub use transform::{
+    AppliedTransform, TransformKind, TransformPipeline, TransformRule, TransformSettings,
+    TransformWarning, TransformedFile,
+};
+pub use upload_metadata::{compute_upload_metadata, generate_upload_metadata_path};
+pub use utils::{
+    CollectedDirectory, CollectedFile, FileCollection, HashAlgorithm, calculate_file_hash_with,
+    collect_files_resilient, collect_files_with_metadata, collect_files_with_policy,
+    read_archive_with_size_check,
+};
 pub use validation::{
-    contains_traversal_attempt, validate_and_create_output_directory, validate_file_size,
+    ArchiveSafetyLimits, OverlapExclusions, PathLimitStrategy, PathLimitViolation, ProtectedKind,
+    ProtectedPath, SafetyLimit, SelectionOverlap, contains_traversal_attempt,
+    resolve_selection_overlaps, validate_and_create_output_directory, validate_file_size,
     validate_paths,
 };
 
@@ -64,6 +112,28 @@ pub struct FileOpsConfig {
     pub preserve_permissions: bool,
     /// Compression level (1-9, higher = smaller but slower)
     pub compression_level: u32,
+    /// How extraction handles entries that exceed platform path limits
+    #[serde(default)]
+    pub path_limit_strategy: PathLimitStrategy,
+    /// Entries extraction writes (default: all)
+    #[serde(default)]
+    pub restore_filter: RestoreFilter,
+    /// Paces extraction writes when set (background I/O priority)
+    #[serde(skip)]
+    pub io_pacer: Option<Arc<IoPacer>>,
+    /// Journal to resume from and record progress in (see `ExtractionJournal`)
+    #[serde(skip)]
+    pub resume_journal: Option<Arc<ExtractionJournal>>,
+    /// OS metadata (glob patterns) exact-match verification doesn't count
+    #[serde(default = "default_verify_ignore")]
+    pub verify_ignore: Vec<String>,
+    /// Limits archives are created under and checked against on extraction
+    #[serde(default)]
+    pub safety_limits: ArchiveSafetyLimits,
+    /// Limits the archive being extracted records it was created under;
+    /// when they satisfy `safety_limits` the extraction pre-scan is skipped
+    #[serde(default)]
+    pub recorded_safety_limits: Option<ArchiveSafetyLimits>,
 }
 
 impl Default for FileOpsConfig {
@@ -73,10 +143,24 @@ fn default() -> Self {
             max_archive_size: MAX_TOTAL_ARCHIVE_SIZE,
             preserve_permissions: true,
             compression_level: 6,
+            path_limit_strategy: PathLimitStrategy::default(),
+            restore_filter: RestoreFilter::default(),
+            io_pacer: None,
+            resume_journal: None,
+            verify_ignore: default_verify_ignore(),
+            safety_limits: ArchiveSafetyLimits::default(),
+            recorded_safety_limits: None,
         }
     }
 }
 
+fn default_verify_ignore() -> Vec<String> {
+    DEFAULT_VERIFY_IGNORE
+        .iter()
+        .map(|pattern| pattern.to_string())
+        .collect()
+}
+
 /// Information about a file operation
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FileInfo {
@@ -93,6 +177,18 @@ pub struct FileInfo {
     pub permissions: u32,
 }
 
+/// Information about a directory, archived as its own entry so empty ones survive
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DirectoryInfo {
+    /// Directory path
+    pub path: PathBuf,
+    /// Directory modification time
+    pub modified: DateTime<Utc>,
+    /// Directory permissions (Unix only)
+    #[cfg(unix)]
+    pub permissions: u32,
+}
+
 /// Information about an archive
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ArchiveInfo {
@@ -126,5 +222,9 @@ pub struct ArchiveOperation {
 // Re-export main functions for convenience
 pub use archive_manifest::create_manifest_for_archive;
 pub use archive_operations::create_archive_with_progress;
+pub use ownership::{
+    FidelityReport, FileOwnership, OwnershipFallback, OwnershipFallbackReason, OwnershipPolicy,
+    SystemUserDatabase, UserDatabase, apply_ownership, record_ownership, resolve_ownership,
+};
 pub use selection::validate_selection;
 pub use staging::create_staging_area;
@@ -0,0 +1,530 @@
+//! File ownership recording and restoration
+//!
+//! Ownership is recorded in the vault manifest at archive time as both names
+//! and numeric IDs. Numeric IDs rarely mean the same thing on another machine,
+//! so extraction maps them according to an `OwnershipPolicy`, and every file
+//! that could not be given its recorded owner is listed in a `FidelityReport`.
+//!
+//! Ownership is Unix-only. On other platforms nothing is recorded and
+//! recorded fields are ignored on extraction.
+
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use tracing::{debug, warn};
+
+/// Owner of a file as recorded at archive time
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+pub struct FileOwnership {
+    pub uid: u32,
+    pub gid: u32,
+    /// User name for `uid` on the archiving machine, if it resolved
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user: Option<String>,
+    /// Group name for `gid` on the archiving machine, if it resolved
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub group: Option<String>,
+}
+
+impl FileOwnership {
+    /// Record numeric IDs along with their names in `db`
+    pub fn from_ids(uid: u32, gid: u32, db: &dyn UserDatabase) -> Self {
+        Self {
+            uid,
+            gid,
+            user: db.user_name(uid),
+            group: db.group_name(gid),
+        }
+    }
+}
+
+/// How recorded ownership is applied to extracted files
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+pub enum OwnershipPolicy {
+    /// Extracted files belong to the user running the extraction
+    #[default]
+    CurrentUser,
+    /// Resolve recorded user and group names on this machine
+    MapByName,
+    /// Restore recorded numeric IDs (requires elevated privileges)
+    PreserveNumeric,
+}
+
+/// Why a file did not receive its recorded owner
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, specta::Type)]
+#[serde(rename_all = "snake_case")]
+pub enum OwnershipFallbackReason {
+    /// Recorded user name does not exist on this machine
+    UnknownUser,
+    /// Recorded group name does not exist on this machine
+    UnknownGroup,
+    /// Manifest has no user name for this file
+    UserNotRecorded,
+    /// Manifest has no group name for this file
+    GroupNotRecorded,
+    /// Changing the owner was refused by the system
+    ChangeFailed,
+}
+
+/// A file left owned by the current user instead of its recorded owner
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, specta::Type)]
+pub struct OwnershipFallback {
+    pub path: String,
+    pub recorded: FileOwnership,
+    pub reason: OwnershipFallbackReason,
+}
+
+/// How faithfully recorded file attributes were restored on extraction
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, specta::Type)]
+pub struct FidelityReport {
+    pub ownership_policy: OwnershipPolicy,
+    /// Files given an owner other than the current user
+    pub ownership_applied: usize,
+    pub ownership_fallbacks: Vec<OwnershipFallback>,
+    /// Conditions affecting every file (e.g. missing privileges)
+    pub warnings: Vec<String>,
+}
+
+/// Local user and group lookups
+///
+/// Injectable so the name-resolution rules can be tested without touching the
+/// system user database.
+pub trait UserDatabase {
+    fn uid_for_user(&self, name: &str) -> Option<u32>;
+    fn gid_for_group(&self, name: &str) -> Option<u32>;
+    fn user_name(&self, uid: u32) -> Option<String>;
+    fn group_name(&self, gid: u32) -> Option<String>;
+    fn current_uid(&self) -> u32;
+    fn current_gid(&self) -> u32;
+    /// True if the process may give files to other users
+    fn is_privileged(&self) -> bool;
+}
+
+/// User database backed by the system's passwd and group entries
+///
+/// Off Unix there are no such entries; every lookup comes back empty.
+#[derive(Debug, Default)]
+pub struct SystemUserDatabase;
+
+#[cfg(unix)]
+impl UserDatabase for SystemUserDatabase {
+    fn uid_for_user(&self, name: &str) -> Option<u32> {
+        nix::unistd::User::from_name(name)
+            .ok()
+            .flatten()
+            .map(|user| user.uid.as_raw())
+    }
+
+    fn gid_for_group(&self, name: &str) -> Option<u32> {
+        nix::unistd::Group::from_name(name)
+            .ok()
+            .flatten()
+            .map(|group| group.gid.as_raw())
+    }
+
+    fn user_name(&self, uid: u32) -> Option<String> {
+        nix::unistd::User::from_uid(nix::unistd::Uid::from_raw(uid))
+            .ok()
+            .flatten()
+            .map(|user| user.name)
+    }
+
+    fn group_name(&self, gid: u32) -> Option<String> {
+        nix::unistd::Group::from_gid(nix::unistd::Gid::from_raw(gid))
+            .ok()
+            .flatten()
+            .map(|group| group.name)
+    }
+
+    fn current_uid(&self) -> u32 {
+        nix::unistd::geteuid().as_raw()
+    }
+
+    fn current_gid(&self) -> u32 {
+        nix::unistd::getegid().as_raw()
+    }
+
+    fn is_privileged(&self) -> bool {
+        nix::unistd::geteuid().is_root()
+    }
+}
+
+#[cfg(not(unix))]
+impl UserDatabase for SystemUserDatabase {
+    fn uid_for_user(&self, _name: &str) -> Option<u32> {
+        None
+    }
+
+    fn gid_for_group(&self, _name: &str) -> Option<u32> {
+        None
+    }
+
+    fn user_name(&self, _uid: u32) -> Option<String> {
+        None
+    }
+
+    fn group_name(&self, _gid: u32) -> Option<String> {
+        None
+    }
+
+    fn current_uid(&self) -> u32 {
+        0
+    }
+
+    fn current_gid(&self) -> u32 {
+        0
+    }
+
+    fn is_privileged(&self) -> bool {
+        false
+    }
+}
+
+/// Ownership of a source file for the manifest (`None` off Unix)
+pub fn record_ownership(metadata: &std::fs::Metadata) -> Option<FileOwnership> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        Some(FileOwnership::from_ids(
+            metadata.uid(),
+            metadata.gid(),
+            &SystemUserDatabase,
+        ))
+    }
+
+    #[cfg(not(unix))]
+    {
+        let _ = metadata;
+        None
+    }
+}
+
+/// Owner chosen for a file, and why it differs from the recorded one
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OwnershipResolution {
+    pub uid: u32,
+    pub gid: u32,
+    pub fallbacks: Vec<OwnershipFallbackReason>,
+}
+
+/// Decide which owner an extracted file should get
+///
+/// `PreserveNumeric` without privileges resolves to the current user with no
+/// per-file fallback; the caller reports it once as a warning.
+pub fn resolve_ownership(
+    recorded: &FileOwnership,
+    policy: OwnershipPolicy,
+    db: &dyn UserDatabase,
+) -> OwnershipResolution {
+    let current = OwnershipResolution {
+        uid: db.current_uid(),
+        gid: db.current_gid(),
+        fallbacks: Vec::new(),
+    };
+
+    match policy {
+        OwnershipPolicy::CurrentUser => current,
+        OwnershipPolicy::PreserveNumeric if db.is_privileged() => OwnershipResolution {
+            uid: recorded.uid,
+            gid: recorded.gid,
+            fallbacks: Vec::new(),
+        },
+        OwnershipPolicy::PreserveNumeric => current,
+        OwnershipPolicy::MapByName => {
+            let mut resolution = current;
+
+            match recorded.user.as_deref() {
+                Some(name) => match db.uid_for_user(name) {
+                    Some(uid) => resolution.uid = uid,
+                    None => resolution
+                        .fallbacks
+                        .push(OwnershipFallbackReason::UnknownUser),
+                },
+                None => resolution
+                    .fallbacks
+                    .push(OwnershipFallbackReason::UserNotRecorded),
+            }
+
+            match recorded.group.as_deref() {
+                Some(name) => match db.gid_for_group(name) {
+                    Some(gid) => resolution.gid = gid,
+                    None => resolution
+                        .fallbacks
+                        .push(OwnershipFallbackReason::UnknownGroup),
+                },
+                None => resolution
+                    .fallbacks
+                    .push(OwnershipFallbackReason::GroupNotRecorded),
+            }
+
+            resolution
+        }
+    }
+}
+
+/// Apply recorded ownership to extracted files
+///
+/// Failures never abort extraction: files that can't be given their recorded
+/// owner stay with the current user and are listed in the report.
+pub fn apply_ownership(
+    files: &[(PathBuf, FileOwnership)],
+    policy: OwnershipPolicy,
+    db: &dyn UserDatabase,
+) -> FidelityReport {
+    let mut report = FidelityReport {
+        ownership_policy: policy,
+        ..Default::default()
+    };
+
+    if policy == OwnershipPolicy::CurrentUser || files.is_empty() {
+        return report;
+    }
+
+    if !cfg!(unix) {
+        debug!("File ownership is not applied on this platform");
+        return report;
+    }
+
+    if policy == OwnershipPolicy::PreserveNumeric && !db.is_privileged() {
+        warn!("Preserving numeric ownership requires elevated privileges; using current user");
+        report.warnings.push(
+            "Numeric ownership requires elevated privileges; files are owned by the current user"
+                .to_string(),
+        );
+        return report;
+    }
+
+    for (path, recorded) in files {
+        let resolution = resolve_ownership(recorded, policy, db);
+        for reason in resolution.fallbacks {
+            report.ownership_fallbacks.push(OwnershipFallback {
+                path: path.to_string_lossy().to_string(),
+                recorded: recorded.clone(),
+                reason,
+            });
+        }
+
+        // Extracted files already belong to the current user
+        if resolution.uid == db.current_uid() && resolution.gid == db.current_gid() {
+            continue;
+        }
+
+        match change_owner(path, resolution.uid, resolution.gid) {
+            Ok(()) => report.ownership_applied += 1,
+            Err(e) => {
+                warn!(path = %path.display(), error = %e, "Failed to change file owner");
+                report.ownership_fallbacks.push(OwnershipFallback {
+                    path: path.to_string_lossy().to_string(),
+                    recorded: recorded.clone(),
+                    reason: OwnershipFallbackReason::ChangeFailed,
+                });
+            }
+        }
+    }
+
+    report
+}
+
+#[cfg(unix)]
+fn change_owner(path: &Path, uid: u32, gid: u32) -> std::io::Result<()> {
+    std::os::unix::fs::chown(path, Some(uid), Some(gid))
+}
+
+#[cfg(not(unix))]
+fn change_owner(_path: &Path, _uid: u32, _gid: u32) -> std::io::Result<()> {
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    struct FakeUserDatabase {
+        users: HashMap<&'static str, u32>,
+        groups: HashMap<&'static str, u32>,
+        privileged: bool,
+    }
+
+    impl FakeUserDatabase {
+        fn new(privileged: bool) -> Self {
+            Self {
+                users: HashMap::from([("alice", 1001), ("me", 500)]),
+                groups: HashMap::from([("staff", 20), ("me", 500)]),
+                privileged,
+            }
+        }
+    }
+
+    impl UserDatabase for FakeUserDatabase {
+        fn uid_for_user(&self, name: &str) -> Option<u32> {
+            self.users.get(name).copied()
+        }
+
+        fn gid_for_group(&self, name: &str) -> Option<u32> {
+            self.groups.get(name).copied()
+        }
+
+        fn user_name(&self, uid: u32) -> Option<String> {
+            self.users
+                .iter()
+                .find(|(_, id)| **id == uid)
+                .map(|(name, _)| name.to_string())
+        }
+
+        fn group_name(&self, gid: u32) -> Option<String> {
+            self.groups
+                .iter()
+                .find(|(_, id)| **id == gid)
+                .map(|(name, _)| name.to_string())
+        }
+
+        fn current_uid(&self) -> u32 {
+            500
+        }
+
+        fn current_gid(&self) -> u32 {
+            500
+        }
+
+        fn is_privileged(&self) -> bool {
+            self.privileged
+        }
+    }
+
+    fn recorded(user: Option<&str>, group: Option<&str>) -> FileOwnership {
+        FileOwnership {
+            uid: 4242,
+            gid: 4343,
+            user: user.map(String::from),
+            group: group.map(String::from),
+        }
+    }
+
+    #[test]
+    fn test_from_ids_records_names() {
+        let db = FakeUserDatabase::new(false);
+        let ownership = FileOwnership::from_ids(1001, 9999, &db);
+
+        assert_eq!(ownership.user.as_deref(), Some("alice"));
+        assert_eq!(ownership.group, None);
+    }
+
+    #[test]
+    fn test_current_user_ignores_recorded_owner() {
+        let db = FakeUserDatabase::new(true);
+        let resolution = resolve_ownership(
+            &recorded(Some("alice"), Some("staff")),
+            OwnershipPolicy::CurrentUser,
+            &db,
+        );
+
+        assert_eq!((resolution.uid, resolution.gid), (500, 500));
+        assert!(resolution.fallbacks.is_empty());
+    }
+
+    #[test]
+    fn test_map_by_name_fallback_matrix() {
+        use OwnershipFallbackReason::*;
+
+        let db = FakeUserDatabase::new(false);
+        let cases = [
+            (Some("alice"), Some("staff"), (1001, 20), vec![]),
+            (Some("bob"), Some("staff"), (500, 20), vec![UnknownUser]),
+            (
+                Some("alice"),
+                Some("wheel"),
+                (1001, 500),
+                vec![UnknownGroup],
+            ),
+            (
+                Some("bob"),
+                Some("wheel"),
+                (500, 500),
+                vec![UnknownUser, UnknownGroup],
+            ),
+            (
+                None,
+                None,
+                (500, 500),
+                vec![UserNotRecorded, GroupNotRecorded],
+            ),
+            (None, Some("staff"), (500, 20), vec![UserNotRecorded]),
+        ];
+
+        for (user, group, expected_ids, expected_fallbacks) in cases {
+            let resolution =
+                resolve_ownership(&recorded(user, group), OwnershipPolicy::MapByName, &db);
+            assert_eq!(
+                (resolution.uid, resolution.gid),
+                expected_ids,
+                "ids for {user:?}:{group:?}"
+            );
+            assert_eq!(
+                resolution.fallbacks, expected_fallbacks,
+                "fallbacks for {user:?}:{group:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_preserve_numeric_requires_privileges() {
+        let owner = recorded(Some("alice"), Some("staff"));
+
+        let privileged = resolve_ownership(
+            &owner,
+            OwnershipPolicy::PreserveNumeric,
+            &FakeUserDatabase::new(true),
+        );
+        assert_eq!((privileged.uid, privileged.gid), (4242, 4343));
+
+        let unprivileged = resolve_ownership(
+            &owner,
+            OwnershipPolicy::PreserveNumeric,
+            &FakeUserDatabase::new(false),
+        );
+        assert_eq!((unprivileged.uid, unprivileged.gid), (500, 500));
+        assert!(unprivileged.fallbacks.is_empty());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_unprivileged_preserve_numeric_warns_once() {
+        let files = vec![
+            (PathBuf::from("a.txt"), recorded(Some("alice"), None)),
+            (PathBuf::from("b.txt"), recorded(Some("alice"), None)),
+        ];
+
+        let report = apply_ownership(
+            &files,
+            OwnershipPolicy::PreserveNumeric,
+            &FakeUserDatabase::new(false),
+        );
+
+        assert_eq!(report.warnings.len(), 1);
+        assert!(report.ownership_fallbacks.is_empty());
+        assert_eq!(report.ownership_applied, 0);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_unknown_names_are_reported_per_file() {
+        let files = vec![
+            (PathBuf::from("a.txt"), recorded(Some("bob"), Some("me"))),
+            (PathBuf::from("b.txt"), recorded(Some("me"), Some("me"))),
+        ];
+
+        let report = apply_ownership(
+            &files,
+            OwnershipPolicy::MapByName,
+            &FakeUserDatabase::new(false),
+        );
+
+        assert_eq!(report.ownership_applied, 0);
+        assert_eq!(report.ownership_fallbacks.len(), 1);
+        assert_eq!(report.ownership_fallbacks[0].path, "a.txt");
+        assert_eq!(
+            report.ownership_fallbacks[0].reason,
+            OwnershipFallbackReason::UnknownUser
+        );
+    }
+}
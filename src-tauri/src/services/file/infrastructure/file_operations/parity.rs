@@ -0,0 +1,933 @@
+//! Reed–Solomon parity sidecars for encrypted archives
+//!
+//! The archive is read as fixed-size blocks grouped into stripes. Each stripe
+//! gets parity blocks computed over GF(2^8) with a Cauchy matrix, so any
+//! damaged blocks in a stripe, up to its parity count, can be rebuilt from
+//! the blocks that survive. Damage is found through SHA-256 hashes of every
+//! block kept in the sidecar's index; none of this needs a key.
+//!
+//! Sidecar layout (`<archive>.par`):
+//!
+//! ```text
+//! [parity blocks, stripe by stripe][index JSON][index length: u64 LE][PARITY_MAGIC]
+//! ```
+//!
+//! The index goes last so parity is generated in one streaming pass. Memory
+//! stays at one stripe (data plus parity) whatever the archive size.
+
+use super::{FileOpsError, Result};
+use crate::services::file::domain::models::{
+    ByteRange, MAX_REDUNDANCY_PERCENT, MIN_REDUNDANCY_PERCENT, ParityCheck, ParityInfo,
+};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs::{self, File};
+use std::io::{BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use tracing::{debug, info, warn};
+
+/// Archive block size; damage is found and repaired per block
+pub const PARITY_BLOCK_SIZE: usize = 64 * 1024;
+
+/// Archive blocks per stripe; one stripe is held in memory at a time
+pub const PARITY_STRIPE_BLOCKS: usize = 64;
+
+/// Sidecar index schema identifier
+const PARITY_SCHEMA: &str = "barqly.vault.parity/1";
+
+/// Last bytes of every sidecar
+const PARITY_MAGIC: &[u8; 8] = b"BQVPAR01";
+
+/// Index length plus magic at the end of the sidecar
+const TRAILER_LEN: u64 = 16;
+
+/// Block geometry of a sidecar
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+struct ParityLayout {
+    block_size: usize,
+    /// Archive blocks per stripe (the last stripe may have fewer)
+    stripe_blocks: usize,
+    /// Parity blocks per stripe
+    parity_blocks: usize,
+}
+
+impl ParityLayout {
+    fn new(block_size: usize, stripe_blocks: usize, redundancy_percent: u8) -> Self {
+        let parity_blocks = (stripe_blocks * usize::from(redundancy_percent))
+            .div_ceil(100)
+            .max(1);
+        Self {
+            block_size,
+            stripe_blocks,
+            parity_blocks,
+        }
+    }
+
+    /// Cauchy points for data and parity rows must be distinct bytes
+    fn is_valid(&self) -> bool {
+        self.block_size > 0
+            && self.stripe_blocks > 0
+            && self.parity_blocks > 0
+            && self.stripe_blocks + self.parity_blocks <= 256
+    }
+
+    fn block_count(&self, archive_size: u64) -> usize {
+        archive_size.div_ceil(self.block_size as u64) as usize
+    }
+
+    fn stripe_count(&self, archive_size: u64) -> usize {
+        self.block_count(archive_size).div_ceil(self.stripe_blocks)
+    }
+
+    /// Coefficient of archive block `col` in parity block `row` of a stripe
+    fn coefficient(&self, row: usize, col: usize) -> u8 {
+        gf_inv((self.stripe_blocks + row) as u8 ^ col as u8)
+    }
+}
+
+/// Index stored at the end of the sidecar
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ParityIndex {
+    schema: String,
+    #[serde(flatten)]
+    layout: ParityLayout,
+    redundancy_percent: u8,
+    archive_size: u64,
+    archive_sha256: String,
+    /// SHA-256 of each archive block, the last one unpadded
+    block_hashes: Vec<String>,
+    /// SHA-256 of each parity block, stripe by stripe
+    parity_hashes: Vec<String>,
+}
+
+/// Generate the sidecar path for an archive (`Vault.age` → `Vault.age.par`)
+pub fn generate_parity_path(archive_path: &Path) -> PathBuf {
+    let mut file_name = archive_path
+        .file_name()
+        .map(|n| n.to_os_string())
+        .unwrap_or_default();
+    file_name.push(".par");
+    archive_path.with_file_name(file_name)
+}
+
+/// Write a parity sidecar beside a finalized archive
+///
+/// `progress` is called with archive bytes processed and the archive size
+/// after every stripe.
+///
+/// # Errors
+/// - `FileOpsError::InvalidSelection` if the redundancy is out of range
+/// - `FileOpsError::FileNotFound` if the archive does not exist
+/// - `FileOpsError::IoError` if the archive can't be read or the sidecar written
+pub fn generate_parity(
+    archive_path: &Path,
+    redundancy_percent: u8,
+    progress: &dyn Fn(u64, u64),
+) -> Result<ParityInfo> {
+    if !(MIN_REDUNDANCY_PERCENT..=MAX_REDUNDANCY_PERCENT).contains(&redundancy_percent) {
+        return Err(FileOpsError::InvalidSelection {
+            message: format!(
+                "Redundancy must be {MIN_REDUNDANCY_PERCENT}-{MAX_REDUNDANCY_PERCENT}%, \
+                 got {redundancy_percent}%"
+            ),
+        });
+    }
+
+    let layout = ParityLayout::new(PARITY_BLOCK_SIZE, PARITY_STRIPE_BLOCKS, redundancy_percent);
+    write_sidecar(
+        archive_path,
+        &generate_parity_path(archive_path),
+        layout,
+        redundancy_percent,
+        progress,
+    )
+}
+
+/// A parity sidecar with its index loaded
+#[derive(Debug)]
+pub struct ParitySidecar {
+    path: PathBuf,
+    index: ParityIndex,
+}
+
+impl ParitySidecar {
+    /// Load a sidecar's index; `None` when there is no sidecar
+    ///
+    /// # Errors
+    /// - `FileOpsError::InvalidArchiveFormat` if the sidecar is damaged or unknown
+    pub fn open(path: &Path) -> Result<Option<Self>> {
+        let mut file = match File::open(path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(source) => return Err(io_error("Failed to open parity sidecar", path)(source)),
+        };
+        let index = read_index(&mut file, path)?;
+        debug!(
+            path = %path.display(),
+            blocks = index.block_hashes.len(),
+            parity_blocks = index.parity_hashes.len(),
+            "Loaded parity sidecar"
+        );
+        Ok(Some(Self {
+            path: path.to_path_buf(),
+            index,
+        }))
+    }
+
+    pub fn redundancy_percent(&self) -> u8 {
+        self.index.redundancy_percent
+    }
+
+    /// Size of the archive the sidecar was generated for
+    pub fn archive_size(&self) -> u64 {
+        self.index.archive_size
+    }
+
+    /// Compare an archive file against the recorded block hashes
+    ///
+    /// Reads the archive and the sidecar block by block.
+    pub fn check(&self, archive_path: &Path) -> Result<ParityCheck> {
+        let layout = self.index.layout;
+        let mut archive = File::open(archive_path).map_err(|_| FileOpsError::FileNotFound {
+            path: archive_path.to_path_buf(),
+        })?;
+        let mut sidecar = self.open_blocks()?;
+        let mut block = vec![0u8; layout.block_size];
+        let mut assessment = Assessment::default();
+
+        for stripe in 0..layout.stripe_count(self.index.archive_size) {
+            let mut damaged = Vec::new();
+            for number in self.stripe_blocks(stripe) {
+                let read = read_block(&mut archive, &mut block)
+                    .map_err(io_error("Failed to read archive", archive_path))?;
+                let expected = self.block_len(number);
+                if read < expected
+                    || hash_hex(&block[..expected]) != self.index.block_hashes[number]
+                {
+                    damaged.push(number);
+                }
+            }
+            let parity = self.read_parity(&mut sidecar, stripe)?;
+            assessment.add_stripe(&damaged, &parity);
+        }
+
+        Ok(assessment.into_check(self))
+    }
+
+    /// Rebuild damaged blocks of an archive held in memory
+    ///
+    /// `archive` is first cut or zero-extended to the recorded size. When the
+    /// returned check is repairable, `archive` holds the original archive,
+    /// verified against its recorded SHA-256; otherwise it is left partly
+    /// repaired and must not be written back.
+    pub fn repair(&self, archive: &mut Vec<u8>) -> Result<ParityCheck> {
+        let layout = self.index.layout;
+        archive.resize(self.index.archive_size as usize, 0);
+        let mut sidecar = self.open_blocks()?;
+        let mut assessment = Assessment::default();
+
+        for stripe in 0..layout.stripe_count(self.index.archive_size) {
+            let numbers = self.stripe_blocks(stripe);
+            let damaged: Vec<usize> = numbers
+                .clone()
+                .filter(|&number| {
+                    hash_hex(&archive[self.block_range(number)]) != self.index.block_hashes[number]
+                })
+                .collect();
+            let parity = self.read_parity(&mut sidecar, stripe)?;
+            if !assessment.add_stripe(&damaged, &parity) || damaged.is_empty() {
+                continue;
+            }
+
+            let mut blocks: Vec<Vec<u8>> = numbers
+                .clone()
+                .map(|number| {
+                    let mut block = archive[self.block_range(number)].to_vec();
+                    block.resize(layout.block_size, 0);
+                    block
+                })
+                .collect();
+            let first = numbers.start;
+            let missing: Vec<usize> = damaged.iter().map(|number| number - first).collect();
+            rebuild_stripe(&layout, &mut blocks, &missing, &parity);
+            for &number in &damaged {
+                let range = self.block_range(number);
+                let len = range.len();
+                archive[range].copy_from_slice(&blocks[number - first][..len]);
+            }
+        }
+
+        let check = assessment.into_check(self);
+        if check.is_repairable()
+            && !check.damaged_ranges.is_empty()
+            && hash_hex(archive) != self.index.archive_sha256
+        {
+            return Err(FileOpsError::HashCalculationFailed {
+                message: "Repaired archive doesn't match its recorded SHA-256".to_string(),
+            });
+        }
+        Ok(check)
+    }
+
+    /// Rewrite the sidecar from an intact archive with the same geometry,
+    /// e.g. after parity blocks were found damaged
+    pub fn regenerate(&self, archive_path: &Path) -> Result<ParityInfo> {
+        write_sidecar(
+            archive_path,
+            &self.path,
+            self.index.layout,
+            self.index.redundancy_percent,
+            &|_, _| {},
+        )
+    }
+
+    fn open_blocks(&self) -> Result<File> {
+        File::open(&self.path).map_err(io_error("Failed to open parity sidecar", &self.path))
+    }
+
+    /// Archive block numbers in a stripe
+    fn stripe_blocks(&self, stripe: usize) -> std::ops::Range<usize> {
+        let layout = self.index.layout;
+        let first = stripe * layout.stripe_blocks;
+        let end = (first + layout.stripe_blocks).min(self.index.block_hashes.len());
+        first..end
+    }
+
+    /// Byte range of an archive block
+    fn block_range(&self, number: usize) -> std::ops::Range<usize> {
+        let start = number * self.index.layout.block_size;
+        start..start + self.block_len(number)
+    }
+
+    fn block_len(&self, number: usize) -> usize {
+        let start = (number * self.index.layout.block_size) as u64;
+        (self.index.archive_size - start).min(self.index.layout.block_size as u64) as usize
+    }
+
+    /// A stripe's parity blocks; `None` for damaged or missing ones
+    fn read_parity(&self, sidecar: &mut File, stripe: usize) -> Result<Vec<Option<Vec<u8>>>> {
+        let layout = self.index.layout;
+        let first = stripe * layout.parity_blocks;
+        sidecar
+            .seek(SeekFrom::Start((first * layout.block_size) as u64))
+            .map_err(io_error("Failed to read parity sidecar", &self.path))?;
+
+        let mut parity = Vec::with_capacity(layout.parity_blocks);
+        for row in 0..layout.parity_blocks {
+            let mut block = vec![0u8; layout.block_size];
+            let read = read_block(sidecar, &mut block)
+                .map_err(io_error("Failed to read parity sidecar", &self.path))?;
+            let intact = read == layout.block_size
+                && hash_hex(&block) == self.index.parity_hashes[first + row];
+            parity.push(intact.then_some(block));
+        }
+        Ok(parity)
+    }
+}
+
+/// Damage found so far, stripe by stripe
+#[derive(Debug, Default)]
+struct Assessment {
+    damaged: Vec<usize>,
+    unrepairable: Vec<usize>,
+    damaged_parity_blocks: usize,
+}
+
+impl Assessment {
+    /// Record a stripe's damage; false when it's beyond the stripe's parity
+    fn add_stripe(&mut self, damaged: &[usize], parity: &[Option<Vec<u8>>]) -> bool {
+        let surviving = parity.iter().filter(|block| block.is_some()).count();
+        self.damaged_parity_blocks += parity.len() - surviving;
+        self.damaged.extend_from_slice(damaged);
+        if damaged.len() > surviving {
+            self.unrepairable.extend_from_slice(damaged);
+            return false;
+        }
+        true
+    }
+
+    fn into_check(self, sidecar: &ParitySidecar) -> ParityCheck {
+        ParityCheck {
+            damaged_ranges: merge_ranges(sidecar, &self.damaged),
+            unrepairable_ranges: merge_ranges(sidecar, &self.unrepairable),
+            damaged_parity_blocks: self.damaged_parity_blocks,
+        }
+    }
+}
+
+/// Byte ranges of archive blocks, adjacent blocks merged
+fn merge_ranges(sidecar: &ParitySidecar, numbers: &[usize]) -> Vec<ByteRange> {
+    let mut ranges: Vec<(usize, usize)> = Vec::new();
+    for &number in numbers {
+        let range = sidecar.block_range(number);
+        match ranges.last_mut() {
+            Some((_, end)) if *end == range.start => *end = range.end,
+            _ => ranges.push((range.start, range.end)),
+        }
+    }
+    ranges
+        .into_iter()
+        .map(|(start, end)| ByteRange {
+            offset: (start as u64).into(),
+            length: ((end - start) as u64).into(),
+        })
+        .collect()
+}
+
+fn write_sidecar(
+    archive_path: &Path,
+    sidecar_path: &Path,
+    layout: ParityLayout,
+    redundancy_percent: u8,
+    progress: &dyn Fn(u64, u64),
+) -> Result<ParityInfo> {
+    let mut partial_name = sidecar_path
+        .file_name()
+        .map(|n| n.to_os_string())
+        .unwrap_or_default();
+    partial_name.push(".partial");
+    let partial_path = sidecar_path.with_file_name(partial_name);
+
+    let written = write_parity_file(
+        archive_path,
+        &partial_path,
+        layout,
+        redundancy_percent,
+        progress,
+    )
+    .and_then(|size| {
+        fs::rename(&partial_path, sidecar_path)
+            .map_err(io_error("Failed to write parity sidecar", sidecar_path))?;
+        Ok(size)
+    });
+    let parity_size = match written {
+        Ok(size) => size,
+        Err(e) => {
+            let _ = fs::remove_file(&partial_path);
+            warn!(archive = %archive_path.display(), error = %e, "Parity generation failed");
+            return Err(e);
+        }
+    };
+
+    info!(
+        sidecar = %sidecar_path.display(),
+        redundancy_percent,
+        parity_size,
+        "Wrote parity sidecar"
+    );
+    Ok(ParityInfo {
+        file_name: sidecar_path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default(),
+        redundancy_percent,
+        parity_size: parity_size.into(),
+    })
+}
+
+/// Stream the archive into parity blocks and the index; returns the sidecar size
+fn write_parity_file(
+    archive_path: &Path,
+    output_path: &Path,
+    layout: ParityLayout,
+    redundancy_percent: u8,
+    progress: &dyn Fn(u64, u64),
+) -> Result<u64> {
+    let mut archive = File::open(archive_path).map_err(|_| FileOpsError::FileNotFound {
+        path: archive_path.to_path_buf(),
+    })?;
+    let archive_size = archive
+        .metadata()
+        .map_err(io_error("Failed to read archive", archive_path))?
+        .len();
+    let write_failed = io_error("Failed to write parity sidecar", output_path);
+    let output = File::create(output_path).map_err(&write_failed)?;
+    let mut writer = BufWriter::new(output);
+
+    let mut stripe = vec![vec![0u8; layout.block_size]; layout.stripe_blocks];
+    let mut parity = vec![vec![0u8; layout.block_size]; layout.parity_blocks];
+    let mut hasher = Sha256::new();
+    let mut block_hashes = Vec::with_capacity(layout.block_count(archive_size));
+    let mut parity_hashes = Vec::new();
+    let mut processed = 0u64;
+
+    while processed < archive_size {
+        let mut filled = 0;
+        while filled < layout.stripe_blocks && processed < archive_size {
+            let block = &mut stripe[filled];
+            let read = read_block(&mut archive, block)
+                .map_err(io_error("Failed to read archive", archive_path))?;
+            if read == 0 {
+                return Err(FileOpsError::InvalidArchiveFormat {
+                    message: "Archive shrank while parity was being generated".to_string(),
+                });
+            }
+            hasher.update(&block[..read]);
+            block_hashes.push(hash_hex(&block[..read]));
+            processed += read as u64;
+            filled += 1;
+        }
+
+        encode_stripe(&layout, &stripe[..filled], &mut parity);
+        for block in &parity {
+            parity_hashes.push(hash_hex(block));
+            writer.write_all(block).map_err(&write_failed)?;
+        }
+        progress(processed, archive_size);
+    }
+
+    let index = ParityIndex {
+        schema: PARITY_SCHEMA.to_string(),
+        layout,
+        redundancy_percent,
+        archive_size,
+        archive_sha256: hex::encode(hasher.finalize()),
+        block_hashes,
+        parity_hashes,
+    };
+    let json = serde_json::to_vec(&index).map_err(|e| FileOpsError::ArchiveCreationFailed {
+        message: format!("Failed to serialize parity index: {e}"),
+    })?;
+    writer.write_all(&json).map_err(&write_failed)?;
+    writer
+        .write_all(&(json.len() as u64).to_le_bytes())
+        .map_err(&write_failed)?;
+    writer.write_all(PARITY_MAGIC).map_err(&write_failed)?;
+
+    let output = writer
+        .into_inner()
+        .map_err(|e| write_failed(e.into_error()))?;
+    output.sync_all().map_err(&write_failed)?;
+    Ok(output.metadata().map_err(&write_failed)?.len())
+}
+
+fn read_index(file: &mut File, path: &Path) -> Result<ParityIndex> {
+    let damaged = |reason: &str| FileOpsError::InvalidArchiveFormat {
+        message: format!("Parity sidecar {} is damaged: {}", path.display(), reason),
+    };
+    let read_failed = io_error("Failed to read parity sidecar", path);
+
+    let len = file.metadata().map_err(&read_failed)?.len();
+    if len < TRAILER_LEN {
+        return Err(damaged("too short"));
+    }
+    let mut trailer = [0u8; TRAILER_LEN as usize];
+    file.seek(SeekFrom::Start(len - TRAILER_LEN))
+        .and_then(|_| file.read_exact(&mut trailer))
+        .map_err(&read_failed)?;
+    if &trailer[8..] != PARITY_MAGIC {
+        return Err(damaged("missing end marker"));
+    }
+    let mut index_len = [0u8; 8];
+    index_len.copy_from_slice(&trailer[..8]);
+    let index_len = u64::from_le_bytes(index_len);
+    if index_len > len - TRAILER_LEN {
+        return Err(damaged("index length out of range"));
+    }
+
+    let mut json = vec![0u8; index_len as usize];
+    file.seek(SeekFrom::Start(len - TRAILER_LEN - index_len))
+        .and_then(|_| file.read_exact(&mut json))
+        .map_err(&read_failed)?;
+    let index: ParityIndex =
+        serde_json::from_slice(&json).map_err(|e| damaged(&format!("unreadable index ({e})")))?;
+
+    let layout = index.layout;
+    if index.schema != PARITY_SCHEMA {
+        return Err(FileOpsError::InvalidArchiveFormat {
+            message: format!("Unsupported parity sidecar schema '{}'", index.schema),
+        });
+    }
+    let parity_len = (index.parity_hashes.len() * layout.block_size) as u64;
+    let consistent = layout.is_valid()
+        && index.block_hashes.len() == layout.block_count(index.archive_size)
+        && index.parity_hashes.len()
+            == layout.stripe_count(index.archive_size) * layout.parity_blocks
+        && parity_len + index_len + TRAILER_LEN == len;
+    if !consistent {
+        return Err(damaged("index doesn't match the sidecar"));
+    }
+    Ok(index)
+}
+
+/// Fill `buf` from `reader`, zero-padding after end of file; returns bytes read
+fn read_block(reader: &mut impl Read, buf: &mut [u8]) -> std::io::Result<usize> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match reader.read(&mut buf[filled..]) {
+            Ok(0) => break,
+            Ok(n) => filled += n,
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+    buf[filled..].fill(0);
+    Ok(filled)
+}
+
+fn hash_hex(data: &[u8]) -> String {
+    hex::encode(Sha256::digest(data))
+}
+
+fn io_error<'a>(message: &'a str, path: &'a Path) -> impl Fn(std::io::Error) -> FileOpsError + 'a {
+    move |source| FileOpsError::IoError {
+        message: format!("{} {}", message, path.display()),
+        source,
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Reed–Solomon erasure coding over GF(2^8)
+// ---------------------------------------------------------------------------
+
+/// x^8 + x^4 + x^3 + x^2 + 1
+const GF_POLYNOMIAL: u16 = 0x11d;
+
+struct GfTables {
+    exp: [u8; 512],
+    log: [u8; 256],
+}
+
+static GF: GfTables = build_gf_tables();
+
+const fn build_gf_tables() -> GfTables {
+    let mut exp = [0u8; 512];
+    let mut log = [0u8; 256];
+    let mut x: u16 = 1;
+    let mut i = 0;
+    while i < 255 {
+        exp[i] = x as u8;
+        log[x as usize] = i as u8;
+        x <<= 1;
+        if x & 0x100 != 0 {
+            x ^= GF_POLYNOMIAL;
+        }
+        i += 1;
+    }
+    while i < 512 {
+        exp[i] = exp[i - 255];
+        i += 1;
+    }
+    GfTables { exp, log }
+}
+
+fn gf_mul(a: u8, b: u8) -> u8 {
+    if a == 0 || b == 0 {
+        return 0;
+    }
+    GF.exp[GF.log[a as usize] as usize + GF.log[b as usize] as usize]
+}
+
+fn gf_inv(a: u8) -> u8 {
+    debug_assert!(a != 0, "zero has no inverse");
+    GF.exp[255 - GF.log[a as usize] as usize]
+}
+
+/// `dst += coefficient * src`, bytewise
+fn mul_add(dst: &mut [u8], src: &[u8], coefficient: u8) {
+    match coefficient {
+        0 => {}
+        1 => dst.iter_mut().zip(src).for_each(|(d, s)| *d ^= s),
+        _ => {
+            let mut table = [0u8; 256];
+            for (value, product) in table.iter_mut().enumerate() {
+                *product = gf_mul(value as u8, coefficient);
+            }
+            dst.iter_mut()
+                .zip(src)
+                .for_each(|(d, s)| *d ^= table[*s as usize]);
+        }
+    }
+}
+
+/// Parity blocks of a stripe's (padded) archive blocks
+fn encode_stripe(layout: &ParityLayout, data: &[Vec<u8>], parity: &mut [Vec<u8>]) {
+    for (row, out) in parity.iter_mut().enumerate() {
+        out.fill(0);
+        for (col, block) in data.iter().enumerate() {
+            mul_add(out, block, layout.coefficient(row, col));
+        }
+    }
+}
+
+/// Rebuild the `missing` blocks of a stripe from the rest and surviving parity
+///
+/// The caller guarantees at least `missing.len()` parity blocks survive.
+fn rebuild_stripe(
+    layout: &ParityLayout,
+    data: &mut [Vec<u8>],
+    missing: &[usize],
+    parity: &[Option<Vec<u8>>],
+) {
+    let rows: Vec<usize> = parity
+        .iter()
+        .enumerate()
+        .filter_map(|(row, block)| block.as_ref().map(|_| row))
+        .take(missing.len())
+        .collect();
+
+    // What the chosen parity rows owe to the missing blocks alone
+    let syndromes: Vec<Vec<u8>> = rows
+        .iter()
+        .map(|&row| {
+            let mut syndrome = parity[row].clone().unwrap_or_default();
+            for (col, block) in data.iter().enumerate() {
+                if !missing.contains(&col) {
+                    mul_add(&mut syndrome, block, layout.coefficient(row, col));
+                }
+            }
+            syndrome
+        })
+        .collect();
+
+    // Every square submatrix of a Cauchy matrix is invertible
+    let matrix: Vec<Vec<u8>> = rows
+        .iter()
+        .map(|&row| {
+            missing
+                .iter()
+                .map(|&col| layout.coefficient(row, col))
+                .collect()
+        })
+        .collect();
+    let inverse = invert(matrix);
+
+    for (coefficients, &col) in inverse.iter().zip(missing) {
+        let block = &mut data[col];
+        block.fill(0);
+        for (syndrome, &coefficient) in syndromes.iter().zip(coefficients) {
+            mul_add(block, syndrome, coefficient);
+        }
+    }
+}
+
+/// Gauss–Jordan inverse of an invertible square matrix over GF(2^8)
+fn invert(mut matrix: Vec<Vec<u8>>) -> Vec<Vec<u8>> {
+    let n = matrix.len();
+    let mut inverse: Vec<Vec<u8>> = (0..n)
+        .map(|i| (0..n).map(|j| u8::from(i == j)).collect())
+        .collect();
+
+    for col in 0..n {
+        let pivot = (col..n)
+            .find(|&row| matrix[row][col] != 0)
+            .expect("Cauchy submatrix is invertible");
+        matrix.swap(col, pivot);
+        inverse.swap(col, pivot);
+
+        let scale = gf_inv(matrix[col][col]);
+        matrix[col].iter_mut().for_each(|x| *x = gf_mul(*x, scale));
+        inverse[col].iter_mut().for_each(|x| *x = gf_mul(*x, scale));
+
+        let pivot_row = matrix[col].clone();
+        let pivot_inverse = inverse[col].clone();
+        for (row, (values, inverse_values)) in matrix.iter_mut().zip(&mut inverse).enumerate() {
+            let factor = values[col];
+            if row == col || factor == 0 {
+                continue;
+            }
+            mul_add(values, &pivot_row, factor);
+            mul_add(inverse_values, &pivot_inverse, factor);
+        }
+    }
+    inverse
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::Rng;
+    use rand::seq::index::sample;
+    use tempfile::TempDir;
+
+    /// 256-byte blocks, 8 per stripe, 2 parity blocks per stripe
+    const TEST_LAYOUT: ParityLayout = ParityLayout {
+        block_size: 256,
+        stripe_blocks: 8,
+        parity_blocks: 2,
+    };
+
+    /// 20 blocks in 3 stripes (8, 8, 4), the last block partial
+    fn fixture(dir: &Path) -> (PathBuf, Vec<u8>, ParitySidecar) {
+        let mut data = vec![0u8; 19 * 256 + 100];
+        rand::thread_rng().fill(&mut data[..]);
+        let archive = dir.join("Vault.age");
+        fs::write(&archive, &data).unwrap();
+
+        let sidecar_path = generate_parity_path(&archive);
+        write_sidecar(&archive, &sidecar_path, TEST_LAYOUT, 25, &|_, _| {}).unwrap();
+        let sidecar = ParitySidecar::open(&sidecar_path).unwrap().unwrap();
+        (archive, data, sidecar)
+    }
+
+    fn corrupt_block(data: &mut [u8], number: usize) {
+        let offset = number * TEST_LAYOUT.block_size + 7;
+        data[offset] ^= 0xff;
+    }
+
+    #[test]
+    fn test_gf_inverse_round_trips() {
+        for a in 1..=255u8 {
+            assert_eq!(gf_mul(a, gf_inv(a)), 1, "{a}");
+        }
+    }
+
+    #[test]
+    fn test_intact_archive_checks_clean() {
+        let temp = TempDir::new().unwrap();
+        let (archive, _, sidecar) = fixture(temp.path());
+
+        assert_eq!(sidecar.redundancy_percent(), 25);
+        assert_eq!(sidecar.index.parity_hashes.len(), 3 * 2);
+        assert!(sidecar.check(&archive).unwrap().is_intact());
+    }
+
+    #[test]
+    fn test_repairs_random_damage_within_budget() {
+        let temp = TempDir::new().unwrap();
+        let (archive, original, sidecar) = fixture(temp.path());
+        let mut rng = rand::thread_rng();
+
+        // Up to the parity count in every stripe, chosen at random
+        let mut damaged = original.clone();
+        let mut corrupted = Vec::new();
+        for (stripe, blocks) in [(0, 8), (1, 8), (2, 4)] {
+            for offset in sample(&mut rng, blocks, 2).into_iter() {
+                let number = stripe * 8 + offset;
+                corrupt_block(&mut damaged, number);
+                corrupted.push(number);
+            }
+        }
+        corrupted.sort_unstable();
+        fs::write(&archive, &damaged).unwrap();
+
+        let check = sidecar.check(&archive).unwrap();
+        assert!(check.is_repairable());
+        let expected: u64 = corrupted
+            .iter()
+            .map(|&n| sidecar.block_range(n).len() as u64)
+            .sum();
+        assert_eq!(check.damaged_bytes(), expected);
+
+        let repaired = sidecar.repair(&mut damaged).unwrap();
+        assert_eq!(repaired, check);
+        assert_eq!(damaged, original);
+    }
+
+    #[test]
+    fn test_reports_damage_beyond_budget_as_unrepairable() {
+        let temp = TempDir::new().unwrap();
+        let (archive, original, sidecar) = fixture(temp.path());
+
+        // Three adjacent blocks in the first stripe, one in the second
+        let mut damaged = original.clone();
+        for number in [2, 3, 4, 9] {
+            corrupt_block(&mut damaged, number);
+        }
+        fs::write(&archive, &damaged).unwrap();
+
+        let check = sidecar.check(&archive).unwrap();
+        assert!(!check.is_repairable());
+        assert_eq!(
+            check.unrepairable_ranges,
+            vec![ByteRange {
+                offset: 512.into(),
+                length: 768.into(),
+            }]
+        );
+        assert_eq!(check.damaged_ranges.len(), 2);
+
+        let repaired = sidecar.repair(&mut damaged).unwrap();
+        assert!(!repaired.is_repairable());
+    }
+
+    #[test]
+    fn test_damaged_parity_counts_against_budget() {
+        let temp = TempDir::new().unwrap();
+        let (archive, original, sidecar) = fixture(temp.path());
+
+        // One parity block of the first stripe lost; one data block still fits
+        let mut parity = fs::read(&sidecar.path).unwrap();
+        parity[10] ^= 0xff;
+        fs::write(&sidecar.path, &parity).unwrap();
+        let mut damaged = original.clone();
+        corrupt_block(&mut damaged, 5);
+
+        let check = sidecar.repair(&mut damaged).unwrap();
+        assert!(check.is_repairable());
+        assert_eq!(check.damaged_parity_blocks, 1);
+        assert_eq!(damaged, original);
+
+        fs::write(&archive, &damaged).unwrap();
+        sidecar.regenerate(&archive).unwrap();
+        let sidecar = ParitySidecar::open(&sidecar.path).unwrap().unwrap();
+        assert!(sidecar.check(&archive).unwrap().is_intact());
+
+        // A second lost data block in the stripe would have been too many
+        corrupt_block(&mut damaged, 5);
+        corrupt_block(&mut damaged, 6);
+        let mut parity = fs::read(&sidecar.path).unwrap();
+        parity[10] ^= 0xff;
+        fs::write(&sidecar.path, &parity).unwrap();
+        assert!(!sidecar.repair(&mut damaged).unwrap().is_repairable());
+    }
+
+    #[test]
+    fn test_truncated_archive_is_repaired() {
+        let temp = TempDir::new().unwrap();
+        let (archive, original, sidecar) = fixture(temp.path());
+
+        fs::write(&archive, &original[..original.len() - 150]).unwrap();
+        let check = sidecar.check(&archive).unwrap();
+        assert_eq!(check.damaged_ranges.len(), 1);
+        assert_eq!(check.damaged_bytes(), 256 + 100);
+
+        let mut truncated = fs::read(&archive).unwrap();
+        assert!(sidecar.repair(&mut truncated).unwrap().is_repairable());
+        assert_eq!(truncated, original);
+    }
+
+    #[test]
+    fn test_generate_parity_streams_with_progress() {
+        let temp = TempDir::new().unwrap();
+        let archive = temp.path().join("Vault.age");
+        fs::write(&archive, vec![0x5a; PARITY_BLOCK_SIZE + 10]).unwrap();
+
+        let calls = std::cell::RefCell::new(Vec::new());
+        let info = generate_parity(&archive, 10, &|done, total| {
+            calls.borrow_mut().push((done, total))
+        })
+        .unwrap();
+
+        assert_eq!(info.file_name, "Vault.age.par");
+        assert_eq!(info.redundancy_percent, 10);
+        let size = (PARITY_BLOCK_SIZE + 10) as u64;
+        assert_eq!(calls.into_inner(), vec![(size, size)]);
+
+        let sidecar = ParitySidecar::open(&generate_parity_path(&archive))
+            .unwrap()
+            .unwrap();
+        assert_eq!(sidecar.index.layout.parity_blocks, 7);
+        assert!(sidecar.check(&archive).unwrap().is_intact());
+
+        assert!(generate_parity(&archive, 0, &|_, _| {}).is_err());
+        assert!(generate_parity(&archive, 101, &|_, _| {}).is_err());
+    }
+
+    #[test]
+    fn test_missing_or_damaged_sidecar() {
+        let temp = TempDir::new().unwrap();
+        assert!(
+            ParitySidecar::open(&temp.path().join("none.par"))
+                .unwrap()
+                .is_none()
+        );
+
+        let garbage = temp.path().join("garbage.par");
+        fs::write(&garbage, b"not a parity sidecar at all").unwrap();
+        assert!(matches!(
+            ParitySidecar::open(&garbage),
+            Err(FileOpsError::InvalidArchiveFormat { .. })
+        ));
+    }
+}
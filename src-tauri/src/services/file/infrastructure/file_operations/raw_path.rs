@@ -0,0 +1,441 @@
+//! Lossless relative paths
+//!
+//! Unix file names are arbitrary bytes and Windows names are UTF-16 that may
+//! hold unpaired surrogates, so not every name fits in a JSON string.
+//! Manifests record a best-effort display string (invalid sequences become
+//! U+FFFD) beside a `RawPath` holding the true name.
+//!
+//! A `RawPath` is the path's components joined with `/`. Each component is
+//! its platform bytes (Unix: the OS bytes; Windows: the WTF-8 form of its
+//! UTF-16 units) with `%`, `/`, `\` and every byte outside valid UTF-8
+//! written as `%XX`. Valid names read as themselves apart from those escapes.
+//!
+//! Archive entry names are always Unicode, so every platform can extract
+//! them: a component that isn't valid Unicode is stored under its escaped
+//! form and renamed to its true name from the manifest after extraction.
+
+use super::{FileInfo, FileOpsError, Result};
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::ffi::{OsStr, OsString};
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use tracing::warn;
+
+/// A relative path recorded losslessly (see the module docs)
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct RawPath(String);
+
+impl RawPath {
+    /// Record the normal components of `path` exactly
+    pub fn from_relative(path: &Path) -> Self {
+        Self(
+            normal_components(path)
+                .map(|name| encode_component(&os_bytes(name)))
+                .collect::<Vec<_>>()
+                .join("/"),
+        )
+    }
+
+    /// Record a path only known as text, e.g. from a manifest that predates
+    /// raw paths
+    ///
+    /// Both separators split components, as they always did for such paths.
+    pub fn from_display(path: &str) -> Self {
+        Self(
+            path.split(['/', '\\'])
+                .filter(|name| !name.is_empty() && *name != ".")
+                .map(|name| encode_component(name.as_bytes()))
+                .collect::<Vec<_>>()
+                .join("/"),
+        )
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// The recorded path, if this platform can hold it
+    ///
+    /// `None` for malformed escapes, components that aren't plain names
+    /// (empty, `.`, `..`, or holding a separator or NUL), and names this
+    /// platform can't represent, such as invalid UTF-8 on Windows.
+    pub fn to_relative(&self) -> Option<PathBuf> {
+        if self.0.is_empty() {
+            return None;
+        }
+
+        let mut path = PathBuf::new();
+        for component in self.0.split('/') {
+            let bytes = decode_component(component)?;
+            if !is_plain_name(&bytes) {
+                return None;
+            }
+            path.push(os_string(bytes)?);
+        }
+        Some(path)
+    }
+}
+
+/// Whether `path` can't be shown as text without losing part of its name
+pub fn is_lossy_name(path: &Path) -> bool {
+    path.to_str().is_none()
+}
+
+/// Name an entry is stored under in the archive
+///
+/// Components that aren't valid Unicode are replaced by their escaped form;
+/// valid paths are used as they are. A sibling literally named like that
+/// escaped form would collide, which no real selection has been seen to do.
+pub fn archive_entry_name(relative: &Path) -> Cow<'_, Path> {
+    if !is_lossy_name(relative) {
+        return Cow::Borrowed(relative);
+    }
+
+    Cow::Owned(
+        normal_components(relative)
+            .map(|name| match name.to_str() {
+                Some(_) => name.to_os_string(),
+                None => OsString::from(encode_component(&os_bytes(name))),
+            })
+            .collect(),
+    )
+}
+
+/// Rename entries extracted under `archive_entry_name` back to their true names
+///
+/// `true_paths` are the archived paths the manifest records, files and
+/// directories, relative to `root`. Components that aren't valid Unicode are
+/// renamed deepest first, and `extracted` follows the renames. Entries that
+/// weren't extracted (a filtered restore) or already carry their true name
+/// (a resumed one) are left alone. Returns how many were renamed.
+pub fn restore_true_names(
+    root: &Path,
+    true_paths: &[PathBuf],
+    extracted: &mut [FileInfo],
+) -> Result<usize> {
+    let mut lossy: Vec<PathBuf> = Vec::new();
+    for path in true_paths {
+        let mut prefix = PathBuf::new();
+        for name in normal_components(path) {
+            prefix.push(name);
+            if name.to_str().is_none() && !lossy.contains(&prefix) {
+                lossy.push(prefix.clone());
+            }
+        }
+    }
+    // Deepest first, so every parent is still under its archive name
+    lossy.sort_by_key(|path| std::cmp::Reverse(path.components().count()));
+
+    let mut renamed = 0;
+    for true_path in &lossy {
+        let (Some(parent), Some(name)) = (true_path.parent(), true_path.file_name()) else {
+            continue;
+        };
+        let stored = archive_entry_name(true_path).into_owned();
+        let restored = archive_entry_name(parent).join(name);
+        let (from, to) = (root.join(&stored), root.join(&restored));
+
+        if fs::symlink_metadata(&from).is_err() {
+            continue;
+        }
+        if fs::symlink_metadata(&to).is_ok() {
+            warn!(path = %from.display(), "True name is already taken; keeping the archive name");
+            continue;
+        }
+        fs::rename(&from, &to).map_err(|e| FileOpsError::IoError {
+            message: format!("Failed to restore the name of '{}'", from.display()),
+            source: e,
+        })?;
+        renamed += 1;
+
+        for file in extracted.iter_mut() {
+            if let Ok(rest) = file.path.strip_prefix(&from) {
+                file.path = to.join(rest);
+            } else if let Ok(rest) = file.path.strip_prefix(&stored) {
+                file.path = restored.join(rest);
+            }
+        }
+    }
+
+    Ok(renamed)
+}
+
+fn normal_components(path: &Path) -> impl Iterator<Item = &OsStr> {
+    path.components().filter_map(|component| match component {
+        Component::Normal(name) => Some(name),
+        _ => None,
+    })
+}
+
+fn encode_component(bytes: &[u8]) -> String {
+    let mut encoded = String::with_capacity(bytes.len());
+    for chunk in bytes.utf8_chunks() {
+        for c in chunk.valid().chars() {
+            match c {
+                '%' | '/' | '\\' => push_escaped(&mut encoded, c as u8),
+                _ => encoded.push(c),
+            }
+        }
+        for byte in chunk.invalid() {
+            push_escaped(&mut encoded, *byte);
+        }
+    }
+    encoded
+}
+
+fn push_escaped(encoded: &mut String, byte: u8) {
+    encoded.push_str(&format!("%{byte:02X}"));
+}
+
+fn decode_component(component: &str) -> Option<Vec<u8>> {
+    let bytes = component.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let hex = bytes.get(i + 1..i + 3)?;
+            if !hex.iter().all(u8::is_ascii_hexdigit) {
+                return None;
+            }
+            decoded.push(u8::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok()?);
+            i += 3;
+        } else {
+            decoded.push(bytes[i]);
+            i += 1;
+        }
+    }
+    Some(decoded)
+}
+
+/// A single path component that can't escape its parent
+fn is_plain_name(bytes: &[u8]) -> bool {
+    let separator = |b: &u8| *b == b'/' || *b == 0 || (cfg!(windows) && *b == b'\\');
+    !bytes.is_empty() && bytes != b"." && bytes != b".." && !bytes.iter().any(separator)
+}
+
+#[cfg(unix)]
+fn os_bytes(name: &OsStr) -> Cow<'_, [u8]> {
+    use std::os::unix::ffi::OsStrExt;
+    Cow::Borrowed(name.as_bytes())
+}
+
+#[cfg(windows)]
+fn os_bytes(name: &OsStr) -> Cow<'_, [u8]> {
+    use std::os::windows::ffi::OsStrExt;
+    Cow::Owned(wtf8_encode(&name.encode_wide().collect::<Vec<_>>()))
+}
+
+#[cfg(not(any(unix, windows)))]
+fn os_bytes(name: &OsStr) -> Cow<'_, [u8]> {
+    match name.to_string_lossy() {
+        Cow::Borrowed(text) => Cow::Borrowed(text.as_bytes()),
+        Cow::Owned(text) => Cow::Owned(text.into_bytes()),
+    }
+}
+
+#[cfg(unix)]
+fn os_string(bytes: Vec<u8>) -> Option<OsString> {
+    use std::os::unix::ffi::OsStringExt;
+    Some(OsString::from_vec(bytes))
+}
+
+#[cfg(windows)]
+fn os_string(bytes: Vec<u8>) -> Option<OsString> {
+    use std::os::windows::ffi::OsStringExt;
+    wtf8_decode(&bytes).map(|units| OsString::from_wide(&units))
+}
+
+#[cfg(not(any(unix, windows)))]
+fn os_string(bytes: Vec<u8>) -> Option<OsString> {
+    String::from_utf8(bytes).ok().map(OsString::from)
+}
+
+/// UTF-16 units as WTF-8: UTF-8 that also encodes unpaired surrogates
+#[cfg(any(windows, test))]
+fn wtf8_encode(units: &[u16]) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(units.len());
+    for unit in char::decode_utf16(units.iter().copied()) {
+        let code_point = match unit {
+            Ok(c) => u32::from(c),
+            Err(e) => u32::from(e.unpaired_surrogate()),
+        };
+        match code_point {
+            0..=0x7F => bytes.push(code_point as u8),
+            0x80..=0x7FF => bytes.extend([
+                0xC0 | (code_point >> 6) as u8,
+                0x80 | (code_point & 0x3F) as u8,
+            ]),
+            0x800..=0xFFFF => bytes.extend([
+                0xE0 | (code_point >> 12) as u8,
+                0x80 | ((code_point >> 6) & 0x3F) as u8,
+                0x80 | (code_point & 0x3F) as u8,
+            ]),
+            _ => bytes.extend([
+                0xF0 | (code_point >> 18) as u8,
+                0x80 | ((code_point >> 12) & 0x3F) as u8,
+                0x80 | ((code_point >> 6) & 0x3F) as u8,
+                0x80 | (code_point & 0x3F) as u8,
+            ]),
+        }
+    }
+    bytes
+}
+
+/// WTF-8 back to UTF-16 units; `None` if the bytes aren't WTF-8
+#[cfg(any(windows, test))]
+fn wtf8_decode(bytes: &[u8]) -> Option<Vec<u16>> {
+    let mut units = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let lead = bytes[i];
+        let (len, min, initial) = match lead {
+            0x00..=0x7F => (1, 0, u32::from(lead)),
+            0xC2..=0xDF => (2, 0x80, u32::from(lead & 0x1F)),
+            0xE0..=0xEF => (3, 0x800, u32::from(lead & 0x0F)),
+            0xF0..=0xF4 => (4, 0x10000, u32::from(lead & 0x07)),
+            _ => return None,
+        };
+        let continuation = bytes.get(i + 1..i + len)?;
+        let mut code_point = initial;
+        for byte in continuation {
+            if byte & 0xC0 != 0x80 {
+                return None;
+            }
+            code_point = (code_point << 6) | u32::from(byte & 0x3F);
+        }
+        if code_point < min || code_point > 0x10FFFF {
+            return None;
+        }
+
+        if code_point >= 0x10000 {
+            let offset = code_point - 0x10000;
+            units.push(0xD800 | (offset >> 10) as u16);
+            units.push(0xDC00 | (offset & 0x3FF) as u16);
+        } else {
+            units.push(code_point as u16);
+        }
+        i += len;
+    }
+
+    // A surrogate pair written as two separate code points isn't WTF-8
+    (wtf8_encode(&units) == bytes).then_some(units)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_valid_names_read_as_themselves() {
+        let raw = RawPath::from_relative(Path::new("docs/100% done.txt"));
+        assert_eq!(raw.as_str(), "docs/100%25 done.txt");
+        assert_eq!(raw.to_relative(), Some(PathBuf::from("docs/100% done.txt")));
+        assert_eq!(RawPath::from_display(r"docs\100% done.txt"), raw);
+    }
+
+    #[test]
+    fn test_unsafe_components_are_rejected() {
+        for text in [
+            "../etc/passwd",
+            "a/%2E%2E/b",
+            "a%2Fb",
+            "a/%00",
+            "a//b",
+            "%4",
+            "%+F",
+        ] {
+            let raw: RawPath = serde_json::from_value(text.into()).unwrap();
+            assert_eq!(raw.to_relative(), None, "{text}");
+        }
+    }
+
+    #[test]
+    fn test_wtf8_round_trips_unpaired_surrogates() {
+        let units = [0x0061, 0xD800, 0x0062, 0xD83D, 0xDE00, 0xDC00];
+        let bytes = wtf8_encode(&units);
+        assert!(std::str::from_utf8(&bytes).is_err());
+        assert_eq!(wtf8_decode(&bytes), Some(units.to_vec()));
+
+        assert_eq!(
+            wtf8_decode("née 😀".as_bytes()),
+            Some("née 😀".encode_utf16().collect())
+        );
+        assert_eq!(wtf8_decode(&[0xFF]), None);
+        // A split surrogate pair must be written as one code point
+        assert_eq!(wtf8_decode(&[0xED, 0xA0, 0xBD, 0xED, 0xB8, 0x80]), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_non_utf8_names_round_trip() {
+        use std::os::unix::ffi::OsStrExt;
+
+        let path = Path::new(OsStr::from_bytes(b"caf\xe9/r\xe9sum\xe9 %.txt"));
+        assert!(is_lossy_name(path));
+
+        let raw = RawPath::from_relative(path);
+        assert_eq!(raw.as_str(), "caf%E9/r%E9sum%E9 %25.txt");
+        assert_eq!(raw.to_relative().as_deref(), Some(path));
+
+        let json = serde_json::to_string(&raw).unwrap();
+        assert_eq!(json, r#""caf%E9/r%E9sum%E9 %25.txt""#);
+        assert_eq!(serde_json::from_str::<RawPath>(&json).unwrap(), raw);
+
+        // Backslashes are part of a Unix name, not separators
+        let backslash = Path::new(OsStr::from_bytes(b"a\\b"));
+        assert_eq!(RawPath::from_relative(backslash).as_str(), "a%5Cb");
+        assert_eq!(
+            RawPath::from_relative(backslash).to_relative().as_deref(),
+            Some(backslash)
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_archive_names_are_restored_to_true_names() {
+        use std::os::unix::ffi::OsStrExt;
+
+        let dir = Path::new(OsStr::from_bytes(b"Records/\xff"));
+        let file = dir.join(OsStr::from_bytes(b"n\xe4me.txt"));
+        assert_eq!(
+            archive_entry_name(&file),
+            Path::new("Records/%FF/n%E4me.txt")
+        );
+        assert_eq!(
+            archive_entry_name(Path::new("Records/plain.txt")),
+            Path::new("Records/plain.txt")
+        );
+
+        let temp = tempfile::TempDir::new().unwrap();
+        let stored = temp.path().join(archive_entry_name(&file));
+        fs::create_dir_all(stored.parent().unwrap()).unwrap();
+        fs::write(&stored, b"contents").unwrap();
+        let mut extracted = vec![FileInfo {
+            path: stored,
+            size: 8,
+            modified: chrono::Utc::now(),
+            hash: String::new(),
+            permissions: 0o644,
+        }];
+
+        let renamed = restore_true_names(
+            temp.path(),
+            &[dir.to_path_buf(), file.clone()],
+            &mut extracted,
+        )
+        .unwrap();
+        assert_eq!(renamed, 2);
+        assert_eq!(extracted[0].path, temp.path().join(&file));
+        assert_eq!(fs::read(temp.path().join(&file)).unwrap(), b"contents");
+
+        // Running again finds nothing left to rename
+        let renamed = restore_true_names(temp.path(), &[file], &mut extracted).unwrap();
+        assert_eq!(renamed, 0);
+    }
+}
@@ -0,0 +1,767 @@
+//! Resilient reading from slow or flaky source media
+//!
+//! Phones mounted over MTP, SD cards in cheap readers and cameras on USB
+//! produce transient read errors (EIO, device busy) and very slow sequential
+//! reads. In resilient-source mode:
+//! - transient errors are retried per file with exponential backoff, re-reading
+//!   the file from the start so no partial data is ever used;
+//! - throughput is sampled on the first few files, and parallelism drops to
+//!   the slow-media setting when the source can't keep up (parallel reads on
+//!   such media only add seeking and bus contention);
+//! - after archiving, a random sample of source files is re-read and compared
+//!   with the manifest hashes to catch silent corruption from flaky readers.
+//!
+//! Reads go through a `SourceReader` so tests can inject failing or slow
+//! sources.
+//!
+//! With an `IoPacer` attached, every chunk is a pacing checkpoint and workers
+//! beyond the pacer's count stop taking files, so an operation switched to
+//! background I/O priority slows down within one chunk.
+
+use super::{FileOpsError, Result};
+use crate::services::shared::infrastructure::io::IoPacer;
+use crate::types::{ByteSize, DurationMs};
+use rand::seq::SliceRandom;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeSet, HashMap};
+use std::fs::File;
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU32, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tracing::{debug, info, warn};
+
+const READ_CHUNK_SIZE: usize = 256 * 1024;
+
+/// Settings for reading from slow or flaky media
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+#[serde(default)]
+pub struct ResilientSourceConfig {
+    /// Extra attempts per file after a transient read error
+    pub read_retries: u32,
+    /// Delay before the first retry; doubles on each further retry
+    pub retry_backoff: DurationMs,
+    /// Files read concurrently on fast media
+    pub parallelism: usize,
+    /// Files read concurrently once slow media is detected
+    pub slow_media_parallelism: usize,
+    /// Sampled throughput below this (per second) counts as slow media
+    pub slow_media_threshold: ByteSize,
+    /// Files read one at a time to sample throughput
+    pub throughput_sample_files: usize,
+    /// Source files re-read and re-hashed after archiving
+    pub verify_sample_size: usize,
+}
+
+impl ResilientSourceConfig {
+    /// Plain sequential reads: no retries, sampling or verification
+    ///
+    /// Used when the source isn't flaky but reads still need pacing.
+    pub fn passthrough() -> Self {
+        Self {
+            read_retries: 0,
+            parallelism: 1,
+            throughput_sample_files: 0,
+            verify_sample_size: 0,
+            ..Self::default()
+        }
+    }
+}
+
+impl Default for ResilientSourceConfig {
+    fn default() -> Self {
+        Self {
+            read_retries: 3,
+            retry_backoff: DurationMs(200),
+            parallelism: 4,
+            slow_media_parallelism: 1,
+            slow_media_threshold: ByteSize(8 * 1024 * 1024),
+            throughput_sample_files: 4,
+            verify_sample_size: 8,
+        }
+    }
+}
+
+/// What happened while reading the source in resilient mode
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+pub struct ResilientSourceReport {
+    /// Retries performed after transient read errors, across all files
+    pub retries: u32,
+    /// Files that needed at least one retry (relative paths)
+    pub retried_files: Vec<String>,
+    /// Throughput measured on the sample files, per second
+    pub sampled_throughput: Option<ByteSize>,
+    /// Whether the source was treated as slow media
+    pub slow_media: bool,
+    /// Files read concurrently after sampling
+    pub parallelism: usize,
+    /// Files re-read after archiving whose hashes matched the manifest
+    pub verified_files: Vec<String>,
+}
+
+/// Opens source files for reading
+pub trait SourceReader: Send + Sync {
+    fn open(&self, path: &Path) -> io::Result<Box<dyn Read + Send>>;
+}
+
+/// Reads straight from the filesystem
+#[derive(Debug, Default)]
+pub struct FsSourceReader;
+
+impl SourceReader for FsSourceReader {
+    fn open(&self, path: &Path) -> io::Result<Box<dyn Read + Send>> {
+        Ok(Box::new(File::open(path)?))
+    }
+}
+
+/// Whether a read error is worth retrying
+///
+/// Covers interrupted/timed-out/busy reads and raw EIO, which MTP and USB
+/// card readers report for dropped transfers.
+pub fn is_transient_read_error(error: &io::Error) -> bool {
+    if matches!(
+        error.kind(),
+        io::ErrorKind::Interrupted
+            | io::ErrorKind::TimedOut
+            | io::ErrorKind::WouldBlock
+            | io::ErrorKind::ResourceBusy
+    ) {
+        return true;
+    }
+
+    #[cfg(unix)]
+    const TRANSIENT_OS_ERRORS: &[i32] = &[5]; // EIO
+    // ERROR_CRC, ERROR_SEM_TIMEOUT, ERROR_BUSY, ERROR_IO_DEVICE
+    #[cfg(windows)]
+    const TRANSIENT_OS_ERRORS: &[i32] = &[23, 121, 170, 1117];
+    #[cfg(not(any(unix, windows)))]
+    const TRANSIENT_OS_ERRORS: &[i32] = &[];
+
+    error
+        .raw_os_error()
+        .is_some_and(|code| TRANSIENT_OS_ERRORS.contains(&code))
+}
+
+/// Reads source files with retries, adaptive parallelism and verification
+///
+/// Shared by the collection (hashing) and archiving (copying) stages so the
+/// report covers the whole operation.
+pub struct ResilientSource {
+    config: ResilientSourceConfig,
+    reader: Arc<dyn SourceReader>,
+    retries: AtomicU32,
+    /// Relative paths of files that needed a retry
+    retried_files: Mutex<BTreeSet<String>>,
+    /// Relative path -> source path, for verification
+    sources: Mutex<HashMap<String, PathBuf>>,
+    bytes_read: AtomicU64,
+    sampled_throughput: Mutex<Option<u64>>,
+    parallelism: AtomicUsize,
+    verified_files: Mutex<Vec<String>>,
+    pacer: Option<Arc<IoPacer>>,
+}
+
+impl std::fmt::Debug for ResilientSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ResilientSource")
+            .field("config", &self.config)
+            .field("retries", &self.retries)
+            .finish_non_exhaustive()
+    }
+}
+
+impl ResilientSource {
+    pub fn new(config: ResilientSourceConfig) -> Self {
+        Self::with_reader(config, Arc::new(FsSourceReader))
+    }
+
+    pub fn with_reader(config: ResilientSourceConfig, reader: Arc<dyn SourceReader>) -> Self {
+        let parallelism = config.parallelism.max(1);
+        Self {
+            config,
+            reader,
+            retries: AtomicU32::new(0),
+            retried_files: Mutex::new(BTreeSet::new()),
+            sources: Mutex::new(HashMap::new()),
+            bytes_read: AtomicU64::new(0),
+            sampled_throughput: Mutex::new(None),
+            parallelism: AtomicUsize::new(parallelism),
+            verified_files: Mutex::new(Vec::new()),
+            pacer: None,
+        }
+    }
+
+    /// Pace reads and writes by the operation's I/O priority
+    pub fn with_pacer(mut self, pacer: Arc<IoPacer>) -> Self {
+        self.pacer = Some(pacer);
+        self
+    }
+
+    pub fn config(&self) -> &ResilientSourceConfig {
+        &self.config
+    }
+
+    /// Size and SHA-256 of a source file, retrying transient errors
+    ///
+    /// Remembers the file under `relative_path` for the verification pass.
+    pub fn hash_file(&self, path: &Path, relative_path: &str) -> Result<(u64, String)> {
+        let result = self.hash_with_retry(path, relative_path)?;
+        self.sources
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(relative_path.to_string(), path.to_path_buf());
+        Ok(result)
+    }
+
+    /// Copy a source file to `dest`, retrying transient errors
+    ///
+    /// A retry truncates `dest` and copies again from the start.
+    pub fn copy_file(&self, source: &Path, dest: &Path, relative_path: &str) -> Result<u64> {
+        self.retrying(source, relative_path, || {
+            let mut out = File::create(dest)?;
+            let copied = self.read_chunks(source, |chunk| out.write_all(chunk))?;
+            out.flush()?;
+            Ok(copied)
+        })
+    }
+
+    /// Run `work` over `items`, downshifting parallelism on slow media
+    ///
+    /// The first `throughput_sample_files` items run one at a time to measure
+    /// throughput; the rest run with the resulting parallelism. Results keep
+    /// the order of `items`. In background priority only one worker takes
+    /// further items; the others resume on the next call.
+    pub fn map_files<I, T>(&self, items: &[I], work: impl Fn(&I) -> T + Sync) -> Vec<T>
+    where
+        I: Sync,
+        T: Send,
+    {
+        let sample_len = self.config.throughput_sample_files.min(items.len());
+        let bytes_before = self.bytes_read.load(Ordering::Relaxed);
+        let started = Instant::now();
+
+        let mut results: Vec<T> = items[..sample_len].iter().map(&work).collect();
+
+        if sample_len > 0 {
+            let sampled_bytes = self.bytes_read.load(Ordering::Relaxed) - bytes_before;
+            self.record_throughput(sampled_bytes, started.elapsed());
+        }
+
+        let rest = &items[sample_len..];
+        let workers = self.parallelism.load(Ordering::Relaxed).min(rest.len());
+        if workers <= 1 {
+            results.extend(rest.iter().map(&work));
+            return results;
+        }
+
+        let next = AtomicUsize::new(0);
+        let slots: Vec<Mutex<Option<T>>> = rest.iter().map(|_| Mutex::new(None)).collect();
+        std::thread::scope(|scope| {
+            for worker in 0..workers {
+                let (next, slots, work) = (&next, &slots, &work);
+                scope.spawn(move || {
+                    loop {
+                        if !self.worker_allowed(worker, workers) {
+                            break;
+                        }
+                        let index = next.fetch_add(1, Ordering::Relaxed);
+                        let Some(item) = rest.get(index) else { break };
+                        let value = work(item);
+                        *slots[index].lock().unwrap_or_else(|e| e.into_inner()) = Some(value);
+                    }
+                });
+            }
+        });
+
+        results.extend(
+            slots
+                .into_iter()
+                .filter_map(|slot| slot.into_inner().unwrap_or_else(|e| e.into_inner())),
+        );
+        results
+    }
+
+    /// Re-read a random sample of collected files and compare with `expected`
+    ///
+    /// `expected` maps relative paths to the SHA-256 recorded in the manifest.
+    /// Files not read through `hash_file` are ignored.
+    pub fn verify_sample(&self, expected: &[(String, String)]) -> Result<Vec<String>> {
+        let sources = self
+            .sources
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone();
+        let candidates: Vec<(&String, &String, &PathBuf)> = expected
+            .iter()
+            .filter_map(|(path, sha256)| sources.get(path).map(|source| (path, sha256, source)))
+            .collect();
+
+        let sample: Vec<_> = candidates
+            .choose_multiple(&mut rand::thread_rng(), self.config.verify_sample_size)
+            .collect();
+
+        let mut verified = Vec::with_capacity(sample.len());
+        let mut mismatched = Vec::new();
+        for (relative_path, sha256, source) in sample {
+            let (_, actual) = self.hash_with_retry(source, relative_path)?;
+            if actual == **sha256 {
+                verified.push((*relative_path).clone());
+            } else {
+                warn!(path = %relative_path, "Source file changed or was misread during archiving");
+                mismatched.push((*relative_path).clone());
+            }
+        }
+
+        if !mismatched.is_empty() {
+            return Err(FileOpsError::ManifestVerificationFailed {
+                message: format!(
+                    "Re-reading the source gave different content for: {}. \
+                     The reader may be unreliable; copy the files locally and retry.",
+                    mismatched.join(", ")
+                ),
+            });
+        }
+
+        info!(
+            verified = verified.len(),
+            "Verified source sample after archiving"
+        );
+        self.verified_files
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .extend(verified.iter().cloned());
+        Ok(verified)
+    }
+
+    /// Snapshot of retries, throughput and verification so far
+    pub fn report(&self) -> ResilientSourceReport {
+        let throughput = *self
+            .sampled_throughput
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        let retried_files: Vec<String> = self
+            .retried_files
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+            .cloned()
+            .collect();
+
+        ResilientSourceReport {
+            retries: self.retries.load(Ordering::Relaxed),
+            retried_files,
+            sampled_throughput: throughput.map(ByteSize),
+            slow_media: throughput.is_some_and(|t| t < self.config.slow_media_threshold.bytes()),
+            parallelism: self.parallelism.load(Ordering::Relaxed),
+            verified_files: self
+                .verified_files
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .clone(),
+        }
+    }
+
+    fn hash_with_retry(&self, path: &Path, relative_path: &str) -> Result<(u64, String)> {
+        self.retrying(path, relative_path, || {
+            let mut hasher = Sha256::new();
+            let size = self.read_chunks(path, |chunk| {
+                hasher.update(chunk);
+                Ok(())
+            })?;
+            Ok((size, hex::encode(hasher.finalize())))
+        })
+    }
+
+    /// Whether worker number `worker` of `workers` may take another item
+    fn worker_allowed(&self, worker: usize, workers: usize) -> bool {
+        self.pacer
+            .as_ref()
+            .is_none_or(|pacer| worker < pacer.worker_count(workers))
+    }
+
+    fn read_chunks(
+        &self,
+        path: &Path,
+        mut sink: impl FnMut(&[u8]) -> io::Result<()>,
+    ) -> io::Result<u64> {
+        let mut reader = self.reader.open(path)?;
+        let mut buffer = vec![0u8; READ_CHUNK_SIZE];
+        let mut total = 0u64;
+        loop {
+            let started = Instant::now();
+            let n = reader.read(&mut buffer)?;
+            if n == 0 {
+                return Ok(total);
+            }
+            sink(&buffer[..n])?;
+            total += n as u64;
+            self.bytes_read.fetch_add(n as u64, Ordering::Relaxed);
+            if let Some(pacer) = &self.pacer {
+                pacer.checkpoint(n as u64, started.elapsed());
+            }
+        }
+    }
+
+    /// Run `op` until it succeeds, fails permanently, or retries run out
+    fn retrying<T>(
+        &self,
+        path: &Path,
+        relative_path: &str,
+        mut op: impl FnMut() -> io::Result<T>,
+    ) -> Result<T> {
+        let mut backoff = Duration::from(self.config.retry_backoff);
+        let mut attempt = 0;
+        loop {
+            match op() {
+                Ok(value) => return Ok(value),
+                Err(e) if attempt < self.config.read_retries && is_transient_read_error(&e) => {
+                    attempt += 1;
+                    self.retries.fetch_add(1, Ordering::Relaxed);
+                    self.retried_files
+                        .lock()
+                        .unwrap_or_else(|e| e.into_inner())
+                        .insert(relative_path.to_string());
+                    debug!(
+                        path = %path.display(),
+                        attempt,
+                        error = %e,
+                        "Transient read error, retrying"
+                    );
+                    std::thread::sleep(backoff);
+                    backoff = backoff.saturating_mul(2);
+                }
+                Err(e) => {
+                    return Err(FileOpsError::IoError {
+                        message: format!(
+                            "Failed to read {} after {} attempt(s): {}",
+                            path.display(),
+                            attempt + 1,
+                            e
+                        ),
+                        source: e,
+                    });
+                }
+            }
+        }
+    }
+
+    fn record_throughput(&self, bytes: u64, elapsed: Duration) {
+        let secs = elapsed.as_secs_f64().max(f64::EPSILON);
+        let throughput = (bytes as f64 / secs) as u64;
+        *self
+            .sampled_throughput
+            .lock()
+            .unwrap_or_else(|e| e.into_inner()) = Some(throughput);
+
+        if throughput < self.config.slow_media_threshold.bytes() {
+            let reduced = self.config.slow_media_parallelism.max(1);
+            self.parallelism.store(reduced, Ordering::Relaxed);
+            info!(
+                throughput = %ByteSize(throughput),
+                parallelism = reduced,
+                "Slow source media detected, reducing parallel reads"
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::shared::infrastructure::io::{OperationPriority, PacingHook};
+    use crate::types::IoPriority;
+    use std::collections::HashSet;
+    use tempfile::TempDir;
+
+    /// Fails the first `failures` opens of each file with EIO-like errors
+    struct FlakyReader {
+        failures: u32,
+        seen: Mutex<HashMap<PathBuf, u32>>,
+    }
+
+    impl SourceReader for FlakyReader {
+        fn open(&self, path: &Path) -> io::Result<Box<dyn Read + Send>> {
+            let mut seen = self.seen.lock().unwrap();
+            let count = seen.entry(path.to_path_buf()).or_default();
+            *count += 1;
+            if *count <= self.failures {
+                return Err(io::Error::new(io::ErrorKind::TimedOut, "device busy"));
+            }
+            Ok(Box::new(File::open(path)?))
+        }
+    }
+
+    /// Sleeps on every read, like a phone over MTP
+    struct SlowReader {
+        delay: Duration,
+    }
+
+    struct SlowRead {
+        inner: File,
+        delay: Duration,
+    }
+
+    impl Read for SlowRead {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            std::thread::sleep(self.delay);
+            self.inner.read(buf)
+        }
+    }
+
+    impl SourceReader for SlowReader {
+        fn open(&self, path: &Path) -> io::Result<Box<dyn Read + Send>> {
+            Ok(Box::new(SlowRead {
+                inner: File::open(path)?,
+                delay: self.delay,
+            }))
+        }
+    }
+
+    fn config() -> ResilientSourceConfig {
+        ResilientSourceConfig {
+            retry_backoff: DurationMs(0),
+            ..Default::default()
+        }
+    }
+
+    fn write_files(dir: &TempDir, count: usize) -> Vec<PathBuf> {
+        (0..count)
+            .map(|i| {
+                let path = dir.path().join(format!("IMG_{i:04}.jpg"));
+                std::fs::write(&path, vec![i as u8; 4096]).unwrap();
+                path
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_transient_errors_are_retried_and_counted() {
+        let dir = TempDir::new().unwrap();
+        let files = write_files(&dir, 2);
+        let reader = Arc::new(FlakyReader {
+            failures: 2,
+            seen: Mutex::new(HashMap::new()),
+        });
+        let source = ResilientSource::with_reader(config(), reader);
+
+        let (size, hash) = source.hash_file(&files[0], "IMG_0000.jpg").unwrap();
+        assert_eq!(size, 4096);
+        assert_eq!(hash, hex::encode(Sha256::digest(vec![0u8; 4096])));
+
+        let dest = dir.path().join("copy.jpg");
+        assert_eq!(
+            source.copy_file(&files[1], &dest, "IMG_0001.jpg").unwrap(),
+            4096
+        );
+        assert_eq!(std::fs::read(&dest).unwrap(), vec![1u8; 4096]);
+
+        let report = source.report();
+        assert_eq!(report.retries, 4);
+        assert_eq!(report.retried_files, vec!["IMG_0000.jpg", "IMG_0001.jpg"]);
+    }
+
+    #[test]
+    fn test_gives_up_after_retries_and_on_permanent_errors() {
+        let dir = TempDir::new().unwrap();
+        let files = write_files(&dir, 1);
+        let reader = Arc::new(FlakyReader {
+            failures: 10,
+            seen: Mutex::new(HashMap::new()),
+        });
+        let source = ResilientSource::with_reader(config(), reader);
+        assert!(source.hash_file(&files[0], "IMG_0000.jpg").is_err());
+        assert_eq!(source.report().retries, 3);
+
+        // Missing files are not transient: no retries
+        let source = ResilientSource::new(config());
+        assert!(
+            source
+                .hash_file(&dir.path().join("gone.jpg"), "gone.jpg")
+                .is_err()
+        );
+        assert_eq!(source.report().retries, 0);
+    }
+
+    #[test]
+    fn test_slow_source_downshifts_parallelism() {
+        let dir = TempDir::new().unwrap();
+        let files = write_files(&dir, 6);
+        let cfg = ResilientSourceConfig {
+            throughput_sample_files: 2,
+            slow_media_threshold: ByteSize(1024 * 1024),
+            ..config()
+        };
+
+        let slow = ResilientSource::with_reader(
+            cfg.clone(),
+            Arc::new(SlowReader {
+                delay: Duration::from_millis(20),
+            }),
+        );
+        let hashes = slow.map_files(&files, |path| slow.hash_file(path, "f").unwrap().0);
+        assert_eq!(hashes, vec![4096; 6]);
+        let report = slow.report();
+        assert!(report.slow_media);
+        assert_eq!(report.parallelism, 1);
+        assert!(report.sampled_throughput.unwrap() < ByteSize(1024 * 1024));
+
+        let fast = ResilientSource::new(ResilientSourceConfig {
+            slow_media_threshold: ByteSize(1),
+            ..cfg
+        });
+        let sizes = fast.map_files(&files, |path| fast.hash_file(path, "f").unwrap().0);
+        assert_eq!(sizes, vec![4096; 6]);
+        assert!(!fast.report().slow_media);
+        assert_eq!(fast.report().parallelism, 4);
+    }
+
+    #[test]
+    fn test_map_files_preserves_order_in_parallel() {
+        let items: Vec<usize> = (0..50).collect();
+        let source = ResilientSource::new(ResilientSourceConfig {
+            throughput_sample_files: 0,
+            ..config()
+        });
+        assert_eq!(
+            source.map_files(&items, |i| i * 2),
+            (0..50).map(|i| i * 2).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_verify_sample_catches_changed_source() {
+        let dir = TempDir::new().unwrap();
+        let files = write_files(&dir, 3);
+        let source = ResilientSource::new(ResilientSourceConfig {
+            verify_sample_size: 2,
+            ..config()
+        });
+
+        let expected: Vec<(String, String)> = files
+            .iter()
+            .map(|path| {
+                let name = path.file_name().unwrap().to_string_lossy().to_string();
+                let (_, hash) = source.hash_file(path, &name).unwrap();
+                (name, hash)
+            })
+            .collect();
+
+        let verified = source.verify_sample(&expected).unwrap();
+        assert_eq!(verified.len(), 2);
+        assert_eq!(source.report().verified_files, verified);
+
+        // Silent corruption: the reader now returns different bytes
+        for path in &files {
+            std::fs::write(path, b"corrupted").unwrap();
+        }
+        let err = source.verify_sample(&expected).unwrap_err();
+        assert!(matches!(
+            err,
+            FileOpsError::ManifestVerificationFailed { .. }
+        ));
+    }
+
+    #[test]
+    fn test_transient_error_classification() {
+        assert!(is_transient_read_error(&io::Error::from(
+            io::ErrorKind::TimedOut
+        )));
+        assert!(is_transient_read_error(&io::Error::from(
+            io::ErrorKind::ResourceBusy
+        )));
+        assert!(!is_transient_read_error(&io::Error::from(
+            io::ErrorKind::NotFound
+        )));
+        assert!(!is_transient_read_error(&io::Error::from(
+            io::ErrorKind::PermissionDenied
+        )));
+        #[cfg(unix)]
+        assert!(is_transient_read_error(&io::Error::from_raw_os_error(5)));
+    }
+
+    /// Records pacing pauses instead of sleeping
+    #[derive(Default)]
+    struct RecordingPacing(Mutex<Vec<Duration>>);
+
+    impl PacingHook for RecordingPacing {
+        fn pause(&self, duration: Duration) {
+            self.0.lock().unwrap().push(duration);
+        }
+    }
+
+    /// Switches the operation to background priority on the second read
+    struct SwitchingReader {
+        priority: OperationPriority,
+    }
+
+    struct SwitchingRead {
+        inner: File,
+        reads: u32,
+        priority: OperationPriority,
+    }
+
+    impl Read for SwitchingRead {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            self.reads += 1;
+            if self.reads == 2 {
+                self.priority.set(IoPriority::Background);
+            }
+            self.inner.read(buf)
+        }
+    }
+
+    impl SourceReader for SwitchingReader {
+        fn open(&self, path: &Path) -> io::Result<Box<dyn Read + Send>> {
+            Ok(Box::new(SwitchingRead {
+                inner: File::open(path)?,
+                reads: 0,
+                priority: self.priority.clone(),
+            }))
+        }
+    }
+
+    #[test]
+    fn test_background_priority_uses_one_worker() {
+        let dir = TempDir::new().unwrap();
+        let files = write_files(&dir, 8);
+        let hook = Arc::new(RecordingPacing::default());
+        let priority = OperationPriority::new(IoPriority::Background);
+        let source = ResilientSource::new(ResilientSourceConfig {
+            throughput_sample_files: 0,
+            ..config()
+        })
+        .with_pacer(Arc::new(IoPacer::with_hook(priority, hook.clone())));
+
+        let threads = Mutex::new(HashSet::new());
+        let results = source.map_files(&files, |path| {
+            threads.lock().unwrap().insert(std::thread::current().id());
+            source.hash_file(path, "file").unwrap().0
+        });
+
+        assert_eq!(results, vec![4096; 8]);
+        assert_eq!(threads.lock().unwrap().len(), 1);
+        assert_eq!(hook.0.lock().unwrap().len(), 8);
+    }
+
+    #[test]
+    fn test_priority_switch_takes_effect_at_next_chunk() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("video.mp4");
+        std::fs::write(&path, vec![7u8; READ_CHUNK_SIZE * 3]).unwrap();
+        let priority = OperationPriority::new(IoPriority::Normal);
+        let hook = Arc::new(RecordingPacing::default());
+        let reader = Arc::new(SwitchingReader {
+            priority: priority.clone(),
+        });
+        let source = ResilientSource::with_reader(config(), reader)
+            .with_pacer(Arc::new(IoPacer::with_hook(priority, hook.clone())));
+
+        let (size, _) = source.hash_file(&path, "video.mp4").unwrap();
+
+        assert_eq!(size, (READ_CHUNK_SIZE * 3) as u64);
+        // The first chunk ran at normal priority, the other two were paced
+        assert_eq!(hook.0.lock().unwrap().len(), 2);
+    }
+}
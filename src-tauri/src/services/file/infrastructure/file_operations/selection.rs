@@ -1,8 +1,8 @@
 //! File and folder selection logic
 
+use super::utils::calculate_file_hash;
 use super::validation::{validate_file_size, validate_paths};
 use super::{FileInfo, FileOpsConfig, FileOpsError, Result};
-use crate::constants::*;
 use serde::{Deserialize, Serialize};
 #[cfg(unix)]
 use std::os::unix::fs::PermissionsExt;
@@ -215,34 +215,3 @@ pub fn validate_selection(selection: &FileSelection, config: &FileOpsConfig) ->
     );
     Ok(())
 }
-
-/// Calculate SHA-256 hash of a file
-fn calculate_file_hash(path: &Path) -> Result<String> {
-    use sha2::{Digest, Sha256};
-    use std::fs::File;
-    use std::io::Read;
-
-    let mut file = File::open(path).map_err(|_e| FileOpsError::FileNotFound {
-        path: path.to_path_buf(),
-    })?;
-
-    let mut hasher = Sha256::new();
-    let mut buffer = [0; IO_BUFFER_SIZE];
-
-    loop {
-        let n = file
-            .read(&mut buffer)
-            .map_err(|e| FileOpsError::HashCalculationFailed {
-                message: format!("Failed to read file: {e}"),
-            })?;
-
-        if n == 0 {
-            break;
-        }
-
-        hasher.update(&buffer[..n]);
-    }
-
-    let result = hasher.finalize();
-    Ok(hex::encode(result))
-}
@@ -1,14 +1,40 @@
 //! Staging area management for secure temporary file operations
-
-use super::{FileInfo, FileOpsError, FileSelection, Result};
-use crate::constants::*;
+//!
+//! Staging areas are created under `staging/` in the app data directory, or
+//! the system temp directory before paths are set up. Each one is registered
+//! as live until it's cleaned up, so storage cleanup can tell leftovers of an
+//! interrupted run from the staging of a running operation.
+
+use super::resilient_source::ResilientSource;
+use super::utils::calculate_file_hash;
+use super::{DirectoryInfo, FileInfo, FileOpsError, FileSelection, Result};
+use crate::services::shared::infrastructure::path_management::SensitiveDirectory;
+use crate::services::shared::infrastructure::{SecureDeleteService, get_app_dir};
+use std::collections::{HashMap, HashSet};
+use std::ffi::OsString;
 use std::fs;
 #[cfg(unix)]
 use std::os::unix::fs::PermissionsExt;
 use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
 use tempfile::{NamedTempFile, TempDir};
 use tracing::{debug, error, info};
 
+/// Folder under the app data directory holding staging areas
+pub const STAGING_DIR: &str = "staging";
+
+/// Staging areas of this process that haven't been cleaned up yet
+static LIVE_STAGING: Mutex<Vec<PathBuf>> = Mutex::new(Vec::new());
+
+/// Whether `path` is the staging area of a running operation
+pub fn is_live_staging(path: &Path) -> bool {
+    LIVE_STAGING
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .iter()
+        .any(|live| live == path)
+}
+
 /// Staging area for temporary file operations
 pub struct StagingArea {
     /// Temporary directory for staging
@@ -18,18 +44,52 @@ pub struct StagingArea {
     staging_path: PathBuf,
     /// Files copied to staging area
     staged_files: Vec<FileInfo>,
+    /// Directories of staged folders, parents first, including empty ones
+    staged_directories: Vec<DirectoryInfo>,
+    /// Source files or subtrees to leave out (e.g. locked files, app storage)
+    excluded: HashSet<PathBuf>,
+    /// Reads source files with retries when set (slow or flaky media)
+    resilient_source: Option<Arc<ResilientSource>>,
+    /// Copies staged in place of source files (e.g. transformed ones)
+    substitutes: HashMap<PathBuf, Substitute>,
     /// Whether the staging area has been cleaned up
     cleaned: bool,
 }
 
+/// A copy staged in place of a source file, and the name it's staged under
+struct Substitute {
+    replacement: PathBuf,
+    file_name: OsString,
+}
+
 impl StagingArea {
     /// Create a new staging area
     pub fn new() -> Result<Self> {
-        let temp_dir = tempfile::tempdir().map_err(|e| FileOpsError::StagingAreaFailed {
+        let staging_root = get_app_dir()
+            .ok()
+            .map(|dir| dir.join(STAGING_DIR))
+            .filter(|root| SensitiveDirectory::create(root).is_ok());
+        match staging_root {
+            Some(root) => Self::new_in(&root),
+            None => Self::with_temp_dir(tempfile::tempdir()),
+        }
+    }
+
+    /// Create a new staging area inside `root`
+    pub fn new_in(root: &Path) -> Result<Self> {
+        Self::with_temp_dir(tempfile::Builder::new().prefix("stage-").tempdir_in(root))
+    }
+
+    fn with_temp_dir(temp_dir: std::io::Result<TempDir>) -> Result<Self> {
+        let temp_dir = temp_dir.map_err(|e| FileOpsError::StagingAreaFailed {
             message: format!("Failed to create temporary directory: {e}"),
         })?;
 
         let staging_path = temp_dir.path().to_path_buf();
+        LIVE_STAGING
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push(staging_path.clone());
 
         info!("Created staging area at: {}", staging_path.display());
 
@@ -37,6 +97,10 @@ pub fn new() -> Result<Self> {
             temp_dir,
             staging_path,
             staged_files: Vec::new(),
+            staged_directories: Vec::new(),
+            excluded: HashSet::new(),
+            resilient_source: None,
+            substitutes: HashMap::new(),
             cleaned: false,
         })
     }
@@ -46,6 +110,88 @@ pub fn path(&self) -> &Path {
         &self.staging_path
     }
 
+    /// Leave these source files, and everything below these directories, out
+    /// of subsequent `stage_files` calls
+    pub fn exclude_paths<I>(&mut self, paths: I)
+    where
+        I: IntoIterator<Item = PathBuf>,
+    {
+        self.excluded.extend(paths);
+    }
+
+    fn is_excluded(&self, path: &Path) -> bool {
+        !self.excluded.is_empty() && path.ancestors().any(|p| self.excluded.contains(p))
+    }
+
+    /// Read source files through `source` in subsequent `stage_files` calls
+    pub fn use_resilient_source(&mut self, source: Arc<ResilientSource>) {
+        self.resilient_source = Some(source);
+    }
+
+    /// Stage `replacement`, named `file_name`, wherever subsequent
+    /// `stage_files` calls meet `source`
+    ///
+    /// The file keeps its place in the tree; only its content and name change.
+    pub fn substitute(&mut self, source: PathBuf, replacement: PathBuf, file_name: OsString) {
+        self.substitutes.insert(
+            source,
+            Substitute {
+                replacement,
+                file_name,
+            },
+        );
+    }
+
+    /// Where `source` is staged, given where it would be staged unchanged
+    fn staged_path(&self, source: &Path, dest: PathBuf) -> PathBuf {
+        match self.substitutes.get(source) {
+            Some(substitute) => dest.with_file_name(&substitute.file_name),
+            None => dest,
+        }
+    }
+
+    /// Size of a staged file, given its source's size
+    fn staged_size(&self, source: &Path, dest: &Path, source_len: u64) -> Result<u64> {
+        if !self.substitutes.contains_key(source) {
+            return Ok(source_len);
+        }
+        fs::metadata(dest)
+            .map(|metadata| metadata.len())
+            .map_err(|e| FileOpsError::IoError {
+                message: format!("Failed to get staged file metadata: {e}"),
+                source: e,
+            })
+    }
+
+    /// Copy a source file into the staging area and hash what was read
+    ///
+    /// In resilient mode the staged copy is hashed instead of re-reading the
+    /// (slow) source a second time. A substituted source is never read; its
+    /// replacement is copied and hashed instead.
+    fn copy_source(&self, source: &Path, dest: &Path, relative_path: &Path) -> Result<String> {
+        if let Some(substitute) = self.substitutes.get(source) {
+            fs::copy(&substitute.replacement, dest).map_err(|e| FileOpsError::IoError {
+                message: format!("Failed to copy file to staging area: {e}"),
+                source: e,
+            })?;
+            return calculate_file_hash(dest);
+        }
+
+        match &self.resilient_source {
+            Some(resilient) => {
+                resilient.copy_file(source, dest, &relative_path.to_string_lossy())?;
+                calculate_file_hash(dest)
+            }
+            None => {
+                fs::copy(source, dest).map_err(|e| FileOpsError::IoError {
+                    message: format!("Failed to copy file to staging area: {e}"),
+                    source: e,
+                })?;
+                calculate_file_hash(source)
+            }
+        }
+    }
+
     /// Copy files from selection to staging area
     pub fn stage_files(&mut self, selection: &FileSelection) -> Result<()> {
         info!(
@@ -79,6 +225,11 @@ fn stage_single_file(&mut self, source: &Path) -> Result<()> {
         debug_assert!(source.exists(), "Source file must exist: {source:?}");
         debug_assert!(!self.cleaned, "Cannot stage files after cleanup");
 
+        if self.is_excluded(source) {
+            debug!("Skipping excluded file: {}", source.display());
+            return Ok(());
+        }
+
         let file_name = source
             .file_name()
             .ok_or_else(|| FileOpsError::PathValidationFailed {
@@ -86,13 +237,10 @@ fn stage_single_file(&mut self, source: &Path) -> Result<()> {
                 reason: "Invalid file name".to_string(),
             })?;
 
-        let dest_path = self.staging_path.join(file_name);
+        let dest_path = self.staged_path(source, self.staging_path.join(file_name));
 
         // Copy file to staging area
-        fs::copy(source, &dest_path).map_err(|e| FileOpsError::IoError {
-            message: format!("Failed to copy file to staging area: {e}"),
-            source: e,
-        })?;
+        let hash = self.copy_source(source, &dest_path, Path::new(file_name))?;
 
         // Get file metadata
         let metadata = fs::metadata(source).map_err(|_e| FileOpsError::FileNotFound {
@@ -101,13 +249,13 @@ fn stage_single_file(&mut self, source: &Path) -> Result<()> {
 
         let file_info = FileInfo {
             path: dest_path.clone(),
-            size: metadata.len(),
+            size: self.staged_size(source, &dest_path, metadata.len())?,
             modified: chrono::DateTime::from(
                 metadata
                     .modified()
                     .unwrap_or_else(|_| std::time::SystemTime::now()),
             ),
-            hash: calculate_file_hash(source)?,
+            hash,
             #[cfg(unix)]
             permissions: metadata.permissions().mode(),
         };
@@ -136,6 +284,21 @@ fn stage_folder(&mut self, folder: &Path) -> Result<()> {
             message: format!("Failed to create staging folder: {e}"),
             source: e,
         })?;
+        self.stage_directory(folder, staging_folder.clone())?;
+
+        // Directories are staged even when every file in them is excluded,
+        // so they are restored empty rather than disappearing
+        for entry in super::utils::walk_directories(folder) {
+            if self.is_excluded(entry.path()) {
+                continue;
+            }
+            let relative_path = entry.path().strip_prefix(folder).map_err(|e| {
+                FileOpsError::CrossPlatformPathError {
+                    message: format!("Failed to get relative path: {e}"),
+                }
+            })?;
+            self.stage_directory(entry.path(), staging_folder.join(relative_path))?;
+        }
 
         // Walk through the folder and copy all files
         for entry in walkdir::WalkDir::new(folder)
@@ -151,13 +314,18 @@ fn stage_folder(&mut self, folder: &Path) -> Result<()> {
                     continue;
                 }
 
+                if self.is_excluded(file_path) {
+                    debug!("Skipping excluded file: {}", file_path.display());
+                    continue;
+                }
+
                 let relative_path = file_path.strip_prefix(folder).map_err(|e| {
                     FileOpsError::CrossPlatformPathError {
                         message: format!("Failed to get relative path: {e}"),
                     }
                 })?;
 
-                let dest_path = staging_folder.join(relative_path);
+                let dest_path = self.staged_path(file_path, staging_folder.join(relative_path));
 
                 // Create parent directories if needed
                 if let Some(parent) = dest_path.parent() {
@@ -168,10 +336,7 @@ fn stage_folder(&mut self, folder: &Path) -> Result<()> {
                 }
 
                 // Copy file
-                fs::copy(entry.path(), &dest_path).map_err(|e| FileOpsError::IoError {
-                    message: format!("Failed to copy file to staging area: {e}"),
-                    source: e,
-                })?;
+                let hash = self.copy_source(entry.path(), &dest_path, relative_path)?;
 
                 // Get file metadata
                 let metadata = entry.metadata().map_err(|_e| FileOpsError::FileNotFound {
@@ -180,13 +345,13 @@ fn stage_folder(&mut self, folder: &Path) -> Result<()> {
 
                 let file_info = FileInfo {
                     path: dest_path.clone(),
-                    size: metadata.len(),
+                    size: self.staged_size(file_path, &dest_path, metadata.len())?,
                     modified: chrono::DateTime::from(
                         metadata
                             .modified()
                             .unwrap_or_else(|_| std::time::SystemTime::now()),
                     ),
-                    hash: calculate_file_hash(entry.path())?,
+                    hash,
                     #[cfg(unix)]
                     permissions: metadata.permissions().mode(),
                 };
@@ -203,11 +368,42 @@ fn stage_folder(&mut self, folder: &Path) -> Result<()> {
         Ok(())
     }
 
+    /// Create a directory in staging, recording the source's timestamp and mode
+    fn stage_directory(&mut self, source: &Path, dest_path: PathBuf) -> Result<()> {
+        fs::create_dir_all(&dest_path).map_err(|e| FileOpsError::IoError {
+            message: format!("Failed to create staging directory: {e}"),
+            source: e,
+        })?;
+
+        let metadata = fs::metadata(source).map_err(|_e| FileOpsError::FileNotFound {
+            path: source.to_path_buf(),
+        })?;
+
+        self.staged_directories.push(DirectoryInfo {
+            path: dest_path,
+            modified: chrono::DateTime::from(
+                metadata
+                    .modified()
+                    .unwrap_or_else(|_| std::time::SystemTime::now()),
+            ),
+            #[cfg(unix)]
+            permissions: metadata.permissions().mode(),
+        });
+        debug!("Staged directory: {}", source.display());
+
+        Ok(())
+    }
+
     /// Get all staged files
     pub fn staged_files(&self) -> &[FileInfo] {
         &self.staged_files
     }
 
+    /// Get all staged directories, parents before children
+    pub fn staged_directories(&self) -> &[DirectoryInfo] {
+        &self.staged_directories
+    }
+
     /// Get total size of staged files
     pub fn total_size(&self) -> u64 {
         self.staged_files.iter().map(|f| f.size).sum()
@@ -319,11 +515,19 @@ pub fn cleanup(&mut self) -> Result<()> {
 
         debug!("Cleaning up staging area: {}", self.staging_path.display());
 
-        // The TempDir will automatically clean up when dropped
-        // But we can also manually clean up if needed
+        // Staged copies are plaintext; delete them with the location's best
+        // strategy rather than leaving it to the TempDir's plain removal
         self.cleaned = true;
+        let deleted = SecureDeleteService::new().delete_dir_all(&self.staging_path);
+        LIVE_STAGING
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .retain(|live| live != &self.staging_path);
+        let honesty = deleted.map_err(|e| FileOpsError::StagingAreaFailed {
+            message: format!("Failed to delete staging area: {e}"),
+        })?;
 
-        info!("Staging area cleanup completed");
+        info!(?honesty, "Staging area cleanup completed");
         Ok(())
     }
 }
@@ -344,34 +548,3 @@ pub fn create_staging_area(selection: &FileSelection) -> Result<StagingArea> {
     staging.stage_files(selection)?;
     Ok(staging)
 }
-
-/// Calculate SHA-256 hash of a file
-fn calculate_file_hash(path: &Path) -> Result<String> {
-    use sha2::{Digest, Sha256};
-    use std::fs::File;
-    use std::io::Read;
-
-    let mut file = File::open(path).map_err(|_e| FileOpsError::FileNotFound {
-        path: path.to_path_buf(),
-    })?;
-
-    let mut hasher = Sha256::new();
-    let mut buffer = [0; IO_BUFFER_SIZE];
-
-    loop {
-        let n = file
-            .read(&mut buffer)
-            .map_err(|e| FileOpsError::HashCalculationFailed {
-                message: format!("Failed to read file: {e}"),
-            })?;
-
-        if n == 0 {
-            break;
-        }
-
-        hasher.update(&buffer[..n]);
-    }
-
-    let result = hasher.finalize();
-    Ok(hex::encode(result))
-}
@@ -0,0 +1,654 @@
+//! Pre-encryption transforms
+//!
+//! Some apps export files far larger than they need to be: uncompressed TIFF
+//! scans, pretty-printed JSON dumps, gzip files written at a low level. A
+//! vault can opt in to rules that convert matching files before they're
+//! archived. The source is never touched: each file is copied into the
+//! pipeline's own staging area and transformed there, and the archive stages
+//! the result in place of the source (see `StagingArea::substitute`).
+//!
+//! Built-in transforms keep the content, not the bytes:
+//!
+//! - `ImageToPng`: decodes the image and writes its pixels as PNG. Embedded
+//!   metadata (EXIF, resolution) isn't carried over, and the file is renamed
+//!   to `.png`
+//! - `JsonMinify`: drops the whitespace between JSON tokens; keys, order and
+//!   number formatting are kept as written
+//! - `GzipNormalize`: recompresses a gzip file at the best level with a bare
+//!   header (no stored name or timestamp), as a single member
+//!
+//! Every result is decoded again and compared with the source before it's
+//! used. A transform that fails or doesn't verify leaves the source in the
+//! archive with a warning; one that doesn't make the file smaller leaves it
+//! silently.
+
+use super::Result;
+use super::content_type::content_type_matches;
+use super::staging::StagingArea;
+use super::utils::calculate_file_hash;
+use crate::services::shared::infrastructure::path_matches_glob;
+use flate2::Compression;
+use flate2::GzBuilder;
+use flate2::read::MultiGzDecoder;
+use image::{ImageFormat, ImageReader};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use tracing::{debug, info, warn};
+
+/// A built-in transform
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, specta::Type)]
+#[serde(rename_all = "snake_case")]
+pub enum TransformKind {
+    /// Re-encode an image as lossless PNG
+    ImageToPng,
+    /// Remove insignificant whitespace from JSON
+    JsonMinify,
+    /// Recompress gzip at the best level with a deterministic header
+    GzipNormalize,
+}
+
+impl TransformKind {
+    /// Extension the transformed file is archived with, when it changes
+    fn output_extension(self) -> Option<&'static str> {
+        match self {
+            Self::ImageToPng => Some("png"),
+            Self::JsonMinify | Self::GzipNormalize => None,
+        }
+    }
+
+    /// Content type of the transformed file, when it changes
+    pub fn output_content_type(self) -> Option<&'static str> {
+        match self {
+            Self::ImageToPng => Some("image/png"),
+            Self::JsonMinify | Self::GzipNormalize => None,
+        }
+    }
+}
+
+/// Which files a transform applies to
+///
+/// A rule names a content type (`image/tiff`, `image/*`), a glob
+/// (`exports/*.json`), or both, in which case a file must match both.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+pub struct TransformRule {
+    pub transform: TransformKind,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content_type: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub glob: Option<String>,
+}
+
+impl TransformRule {
+    /// Whether the file at `relative_path` with the detected `content_type`
+    /// matches this rule
+    pub fn matches(&self, relative_path: &str, content_type: Option<&str>) -> bool {
+        let type_matches = self.content_type.as_deref().is_none_or(|pattern| {
+            content_type.is_some_and(|detected| content_type_matches(pattern, detected))
+        });
+        let glob_matches = self
+            .glob
+            .as_deref()
+            .is_none_or(|pattern| path_matches_glob(pattern, relative_path));
+        type_matches && glob_matches
+    }
+}
+
+/// A vault's transform rules; off unless enabled
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+pub struct TransformSettings {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Checked in order; the first matching rule applies
+    #[serde(default)]
+    pub rules: Vec<TransformRule>,
+}
+
+impl TransformSettings {
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Check every rule names something to match
+    pub fn validate(&self) -> std::result::Result<(), String> {
+        for (index, rule) in self.rules.iter().enumerate() {
+            let content_type = rule.content_type.as_deref().map(str::trim);
+            let glob = rule.glob.as_deref().map(str::trim);
+            if content_type.is_none_or(str::is_empty) && glob.is_none_or(str::is_empty) {
+                return Err(format!(
+                    "Transform rule {} needs a content type or a glob pattern",
+                    index + 1
+                ));
+            }
+            if let Some(pattern) = content_type
+                && !pattern.is_empty()
+                && !pattern.contains('/')
+            {
+                return Err(format!(
+                    "Transform rule {}: '{}' isn't a content type such as image/tiff",
+                    index + 1,
+                    pattern
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Provenance of a transformed file, recorded on its manifest entry
+///
+/// The entry's own hash and size describe the archived (transformed) file;
+/// these describe the source it was made from.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AppliedTransform {
+    pub transform: TransformKind,
+    /// Relative path of the source, when the transform renamed the file
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub original_path: Option<String>,
+    pub original_size: u64,
+    pub original_sha256: String,
+}
+
+/// A file that was archived untransformed because its transform failed
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+pub struct TransformWarning {
+    pub path: String,
+    pub transform: TransformKind,
+    pub reason: String,
+}
+
+/// A transformed copy, ready to be staged in place of its source
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TransformedFile {
+    pub source_path: PathBuf,
+    /// The transformed copy in the pipeline's staging area
+    pub staged_path: PathBuf,
+    /// Relative path the file is archived under
+    pub relative_path: String,
+    pub transform: TransformKind,
+    pub size: u64,
+    pub sha256: String,
+}
+
+impl TransformedFile {
+    /// Name the file is archived under
+    pub fn file_name(&self) -> &std::ffi::OsStr {
+        self.staged_path.file_name().unwrap_or_default()
+    }
+}
+
+/// Applies a vault's transform rules to copies of its files
+pub struct TransformPipeline {
+    rules: Vec<TransformRule>,
+    staging: StagingArea,
+    /// Relative paths in use, lowercased, so a rename never collides
+    taken: HashSet<String>,
+    transformed: Vec<TransformedFile>,
+}
+
+impl TransformPipeline {
+    /// A pipeline for `settings`, or `None` when transforms are off or there
+    /// are no rules
+    pub fn from_settings(settings: &TransformSettings) -> Result<Option<Self>> {
+        if !settings.enabled || settings.rules.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(Self::with_staging(
+            settings.rules.clone(),
+            StagingArea::new()?,
+        )))
+    }
+
+    /// A pipeline writing its copies to `staging`
+    pub fn with_staging(rules: Vec<TransformRule>, staging: StagingArea) -> Self {
+        Self {
+            rules,
+            staging,
+            taken: HashSet::new(),
+            transformed: Vec::new(),
+        }
+    }
+
+    /// Record paths already in the archive, which renamed files must avoid
+    pub fn reserve<'a>(&mut self, relative_paths: impl IntoIterator<Item = &'a str>) {
+        self.taken
+            .extend(relative_paths.into_iter().map(str::to_lowercase));
+    }
+
+    /// The transform the first matching rule names
+    pub fn transform_for(
+        &self,
+        relative_path: &str,
+        content_type: Option<&str>,
+    ) -> Option<TransformKind> {
+        self.rules
+            .iter()
+            .find(|rule| rule.matches(relative_path, content_type))
+            .map(|rule| rule.transform)
+    }
+
+    /// Transform a copy of `source` if a rule matches it
+    ///
+    /// `None` when no rule matches or the result wouldn't be smaller; a
+    /// warning when the transform failed and the source should be archived
+    /// as it is.
+    pub fn apply(
+        &mut self,
+        source: &Path,
+        relative_path: &str,
+        content_type: Option<&str>,
+    ) -> Option<std::result::Result<TransformedFile, TransformWarning>> {
+        let transform = self.transform_for(relative_path, content_type)?;
+        let warning = |reason: String| {
+            warn!(path = %relative_path, ?transform, %reason, "Transform failed; archiving the original");
+            TransformWarning {
+                path: relative_path.to_string(),
+                transform,
+                reason,
+            }
+        };
+
+        let archived_path = match transform.output_extension() {
+            Some(extension) => {
+                let renamed = Path::new(relative_path)
+                    .with_extension(extension)
+                    .to_string_lossy()
+                    .into_owned();
+                if renamed != relative_path && self.taken.contains(&renamed.to_lowercase()) {
+                    return Some(Err(warning(format!(
+                        "'{renamed}' is already in the selection"
+                    ))));
+                }
+                renamed
+            }
+            None => relative_path.to_string(),
+        };
+
+        let workdir = self
+            .staging
+            .path()
+            .join(format!("{}", self.transformed.len()));
+        let file_name = Path::new(&archived_path)
+            .file_name()
+            .map(|name| name.to_os_string())
+            .unwrap_or_else(|| "transformed".into());
+        let input = workdir.join("source");
+        let output = workdir.join("out").join(file_name);
+
+        let result = fs::create_dir_all(workdir.join("out"))
+            .and_then(|()| fs::copy(source, &input))
+            .map_err(|e| format!("Failed to copy the file for transforming: {e}"))
+            .and_then(|_| run_transform(transform, &input, &output));
+        let _ = fs::remove_file(&input);
+        if let Err(reason) = result {
+            let _ = fs::remove_file(&output);
+            return Some(Err(warning(reason)));
+        }
+
+        let (original_size, size) = match (fs::metadata(source), fs::metadata(&output)) {
+            (Ok(original), Ok(transformed)) => (original.len(), transformed.len()),
+            (Err(e), _) | (_, Err(e)) => {
+                let _ = fs::remove_file(&output);
+                return Some(Err(warning(format!("Failed to read file size: {e}"))));
+            }
+        };
+        if size >= original_size {
+            debug!(path = %relative_path, ?transform, original_size, size, "Transform didn't shrink the file; archiving the original");
+            let _ = fs::remove_file(&output);
+            return None;
+        }
+
+        let sha256 = match calculate_file_hash(&output) {
+            Ok(sha256) => sha256,
+            Err(e) => {
+                let _ = fs::remove_file(&output);
+                return Some(Err(warning(format!("Failed to hash the result: {e}"))));
+            }
+        };
+
+        info!(path = %relative_path, ?transform, original_size, size, "Transformed file for archiving");
+        self.taken.insert(archived_path.to_lowercase());
+        let transformed = TransformedFile {
+            source_path: source.to_path_buf(),
+            staged_path: output,
+            relative_path: archived_path,
+            transform,
+            size,
+            sha256,
+        };
+        self.transformed.push(transformed.clone());
+        Some(Ok(transformed))
+    }
+
+    /// Every file transformed so far
+    pub fn transformed(&self) -> &[TransformedFile] {
+        &self.transformed
+    }
+}
+
+/// Write the transform of `input` to `output` and check it decodes to the
+/// same content
+fn run_transform(
+    transform: TransformKind,
+    input: &Path,
+    output: &Path,
+) -> std::result::Result<(), String> {
+    match transform {
+        TransformKind::ImageToPng => image_to_png(input, output),
+        TransformKind::JsonMinify => json_minify(input, output),
+        TransformKind::GzipNormalize => gzip_normalize(input, output),
+    }
+}
+
+fn image_to_png(input: &Path, output: &Path) -> std::result::Result<(), String> {
+    let decode = |path: &Path| {
+        ImageReader::open(path)
+            .map_err(|e| e.to_string())?
+            .with_guessed_format()
+            .map_err(|e| e.to_string())?
+            .decode()
+            .map_err(|e| e.to_string())
+    };
+
+    let image = decode(input).map_err(|e| format!("Not a readable image: {e}"))?;
+    image
+        .save_with_format(output, ImageFormat::Png)
+        .map_err(|e| format!("Failed to write PNG: {e}"))?;
+
+    let reencoded = decode(output).map_err(|e| format!("PNG doesn't read back: {e}"))?;
+    if reencoded.color() != image.color()
+        || (reencoded.width(), reencoded.height()) != (image.width(), image.height())
+        || reencoded.as_bytes() != image.as_bytes()
+    {
+        return Err("PNG pixels differ from the original".to_string());
+    }
+    Ok(())
+}
+
+fn json_minify(input: &Path, output: &Path) -> std::result::Result<(), String> {
+    let parse = |path: &Path| -> std::result::Result<serde_json::Value, String> {
+        let file = File::open(path).map_err(|e| e.to_string())?;
+        serde_json::from_reader(BufReader::new(file)).map_err(|e| e.to_string())
+    };
+
+    let original = parse(input).map_err(|e| format!("Not valid JSON: {e}"))?;
+    minify_json_stream(
+        BufReader::new(File::open(input).map_err(|e| e.to_string())?),
+        BufWriter::new(File::create(output).map_err(|e| e.to_string())?),
+    )
+    .map_err(|e| format!("Failed to write minified JSON: {e}"))?;
+
+    let minified = parse(output).map_err(|e| format!("Minified JSON doesn't parse: {e}"))?;
+    if minified != original {
+        return Err("Minified JSON differs from the original".to_string());
+    }
+    Ok(())
+}
+
+/// Copy JSON from `reader` to `writer` without whitespace between tokens
+fn minify_json_stream(reader: impl Read, mut writer: impl Write) -> io::Result<()> {
+    let mut in_string = false;
+    let mut escaped = false;
+    for byte in reader.bytes() {
+        let byte = byte?;
+        if in_string {
+            if escaped {
+                escaped = false;
+            } else if byte == b'\\' {
+                escaped = true;
+            } else if byte == b'"' {
+                in_string = false;
+            }
+        } else if matches!(byte, b' ' | b'\t' | b'\n' | b'\r') {
+            continue;
+        } else if byte == b'"' {
+            in_string = true;
+        }
+        writer.write_all(&[byte])?;
+    }
+    writer.flush()
+}
+
+fn gzip_normalize(input: &Path, output: &Path) -> std::result::Result<(), String> {
+    let digest = |path: &Path| -> std::result::Result<Vec<u8>, String> {
+        let file = File::open(path).map_err(|e| e.to_string())?;
+        let mut decoder = MultiGzDecoder::new(BufReader::new(file));
+        let mut hasher = Sha256::new();
+        let mut buffer = [0u8; 64 * 1024];
+        loop {
+            let read = decoder.read(&mut buffer).map_err(|e| e.to_string())?;
+            if read == 0 {
+                return Ok(hasher.finalize().to_vec());
+            }
+            hasher.update(&buffer[..read]);
+        }
+    };
+
+    let original = digest(input).map_err(|e| format!("Not a readable gzip file: {e}"))?;
+    let file = File::open(input).map_err(|e| e.to_string())?;
+    let mut decoder = MultiGzDecoder::new(BufReader::new(file));
+    let target = File::create(output).map_err(|e| e.to_string())?;
+    let mut encoder = GzBuilder::new()
+        .mtime(0)
+        .write(BufWriter::new(target), Compression::best());
+    io::copy(&mut decoder, &mut encoder)
+        .and_then(|_| encoder.finish()?.flush())
+        .map_err(|e| format!("Failed to recompress: {e}"))?;
+
+    let normalized =
+        digest(output).map_err(|e| format!("Recompressed file doesn't read back: {e}"))?;
+    if normalized != original {
+        return Err("Recompressed content differs from the original".to_string());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::write::GzEncoder;
+    use image::{Rgb, RgbImage};
+    use tempfile::TempDir;
+
+    fn rule(
+        transform: TransformKind,
+        content_type: Option<&str>,
+        glob: Option<&str>,
+    ) -> TransformRule {
+        TransformRule {
+            transform,
+            content_type: content_type.map(str::to_string),
+            glob: glob.map(str::to_string),
+        }
+    }
+
+    fn pipeline(temp: &TempDir, rules: Vec<TransformRule>) -> TransformPipeline {
+        let staging = StagingArea::new_in(temp.path()).unwrap();
+        TransformPipeline::with_staging(rules, staging)
+    }
+
+    #[test]
+    fn test_tiff_becomes_png_with_identical_pixels() {
+        let temp = TempDir::new().unwrap();
+        let source = temp.path().join("scan.tiff");
+        let image = RgbImage::from_fn(64, 48, |x, y| Rgb([(x * 4) as u8, (y * 5) as u8, 7]));
+        image.save_with_format(&source, ImageFormat::Tiff).unwrap();
+        let before = fs::read(&source).unwrap();
+
+        let mut pipeline = pipeline(
+            &temp,
+            vec![rule(TransformKind::ImageToPng, Some("image/tiff"), None)],
+        );
+        let transformed = pipeline
+            .apply(&source, "scans/scan.tiff", Some("image/tiff"))
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(transformed.relative_path, "scans/scan.png");
+        assert_eq!(transformed.file_name(), "scan.png");
+        assert_eq!(
+            fs::read(&source).unwrap(),
+            before,
+            "source must be untouched"
+        );
+        let png = image::open(&transformed.staged_path).unwrap().to_rgb8();
+        assert_eq!(png, image);
+        assert_eq!(
+            calculate_file_hash(&transformed.staged_path).unwrap(),
+            transformed.sha256
+        );
+        assert!(transformed.size < before.len() as u64);
+    }
+
+    #[test]
+    fn test_json_minify_keeps_values_and_key_order() {
+        let temp = TempDir::new().unwrap();
+        let source = temp.path().join("export.json");
+        let json = "{\n  \"zeta\": [1, 2.50, 1e3],\n  \"alpha\": \"a \\\" spaced\\tvalue \",\n  \"nested\": { \"empty\": {} }\n}\n";
+        fs::write(&source, json).unwrap();
+
+        let mut pipeline = pipeline(
+            &temp,
+            vec![rule(TransformKind::JsonMinify, None, Some("*.json"))],
+        );
+        let transformed = pipeline
+            .apply(&source, "export.json", Some("application/json"))
+            .unwrap()
+            .unwrap();
+
+        let minified = fs::read_to_string(&transformed.staged_path).unwrap();
+        assert_eq!(
+            minified,
+            "{\"zeta\":[1,2.50,1e3],\"alpha\":\"a \\\" spaced\\tvalue \",\"nested\":{\"empty\":{}}}"
+        );
+        assert_eq!(transformed.relative_path, "export.json");
+        let original: serde_json::Value = serde_json::from_str(json).unwrap();
+        let round_trip: serde_json::Value = serde_json::from_str(&minified).unwrap();
+        assert_eq!(round_trip, original);
+    }
+
+    #[test]
+    fn test_gzip_normalize_keeps_decompressed_content() {
+        let temp = TempDir::new().unwrap();
+        let source = temp.path().join("dump.sql.gz");
+        let content = "INSERT INTO t VALUES (1, 'row');\n".repeat(2000);
+        // Two members written at the fastest level
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::fast());
+        encoder.write_all(content[..1000].as_bytes()).unwrap();
+        let mut compressed = encoder.finish().unwrap();
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::fast());
+        encoder.write_all(content[1000..].as_bytes()).unwrap();
+        compressed.extend(encoder.finish().unwrap());
+        fs::write(&source, &compressed).unwrap();
+
+        let mut pipeline = pipeline(
+            &temp,
+            vec![rule(
+                TransformKind::GzipNormalize,
+                Some("application/gzip"),
+                None,
+            )],
+        );
+        let transformed = pipeline
+            .apply(&source, "dump.sql.gz", Some("application/gzip"))
+            .unwrap()
+            .unwrap();
+
+        let mut restored = String::new();
+        MultiGzDecoder::new(File::open(&transformed.staged_path).unwrap())
+            .read_to_string(&mut restored)
+            .unwrap();
+        assert_eq!(restored, content);
+        assert!(transformed.size < compressed.len() as u64);
+    }
+
+    #[test]
+    fn test_failed_transform_falls_back_with_a_warning() {
+        let temp = TempDir::new().unwrap();
+        let source = temp.path().join("broken.json");
+        fs::write(&source, "{ \"unterminated\": [1, 2 ").unwrap();
+        let scan = temp.path().join("scan.tif");
+        fs::write(&scan, b"II*\x00 not really a tiff").unwrap();
+
+        let mut pipeline = pipeline(
+            &temp,
+            vec![
+                rule(TransformKind::JsonMinify, None, Some("*.json")),
+                rule(TransformKind::ImageToPng, Some("image/*"), None),
+            ],
+        );
+        pipeline.reserve(["docs/photo.png"]);
+
+        let warning = pipeline
+            .apply(&source, "broken.json", None)
+            .unwrap()
+            .unwrap_err();
+        assert_eq!(warning.transform, TransformKind::JsonMinify);
+        assert!(
+            warning.reason.contains("Not valid JSON"),
+            "{}",
+            warning.reason
+        );
+
+        let warning = pipeline
+            .apply(&scan, "scan.tif", Some("image/tiff"))
+            .unwrap()
+            .unwrap_err();
+        assert_eq!(warning.transform, TransformKind::ImageToPng);
+
+        // A rename onto a file already in the selection is refused
+        let photo = temp.path().join("photo.tiff");
+        RgbImage::new(8, 8)
+            .save_with_format(&photo, ImageFormat::Tiff)
+            .unwrap();
+        let warning = pipeline
+            .apply(&photo, "docs/photo.tiff", Some("image/tiff"))
+            .unwrap()
+            .unwrap_err();
+        assert!(warning.reason.contains("docs/photo.png"));
+
+        // Unmatched files and results that aren't smaller are left alone
+        assert!(
+            pipeline
+                .apply(&source, "notes.txt", Some("text/plain"))
+                .is_none()
+        );
+        let compact = temp.path().join("compact.json");
+        fs::write(&compact, "[1,2]").unwrap();
+        assert!(pipeline.apply(&compact, "compact.json", None).is_none());
+        assert!(pipeline.transformed().is_empty());
+    }
+
+    #[test]
+    fn test_settings_are_off_by_default_and_validated() {
+        let settings = TransformSettings::default();
+        assert!(settings.is_default());
+        assert!(
+            TransformPipeline::from_settings(&settings)
+                .unwrap()
+                .is_none()
+        );
+
+        let mut settings = TransformSettings {
+            enabled: true,
+            rules: vec![rule(TransformKind::JsonMinify, None, None)],
+        };
+        assert!(settings.validate().is_err());
+        settings.rules = vec![rule(TransformKind::ImageToPng, Some("tiff"), None)];
+        assert!(settings.validate().is_err());
+        settings.rules = vec![rule(TransformKind::ImageToPng, Some("image/tiff"), None)];
+        assert!(settings.validate().is_ok());
+
+        let both = rule(
+            TransformKind::JsonMinify,
+            Some("application/json"),
+            Some("exports/*"),
+        );
+        assert!(both.matches("exports/a.json", Some("application/json")));
+        assert!(!both.matches("other/a.json", Some("application/json")));
+        assert!(!both.matches("exports/a.json", None));
+    }
+}
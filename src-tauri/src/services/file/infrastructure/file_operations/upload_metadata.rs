@@ -0,0 +1,416 @@
+//! Upload metadata computation for encrypted archives
+//!
+//! Computes the checksums S3-compatible multipart uploaders need (whole-file
+//! SHA-256/MD5, per-part MD5s and the composite ETag) in a single pass over
+//! the archive, so memory stays bounded regardless of archive size.
+//!
+//! Results are cached in a sidecar JSON next to the archive
+//! (`<archive>.upload.json`). The cache is keyed by a size + modification time
+//! fingerprint and the part size, so an unchanged archive is answered without
+//! re-reading it. Entries for other part sizes survive a recompute only while
+//! the archive's SHA-256 stays the same.
+
+use super::hash_read::read_for_hash;
+use super::{FileOpsError, Result};
+use crate::services::file::domain::models::{UploadMetadata, UploadPartChecksum};
+use crate::types::ByteSize;
+use base64::Engine as _;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use md5::Md5;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+use tracing::{debug, info, warn};
+
+/// Maximum number of parts allowed by the S3 multipart API
+pub const MAX_UPLOAD_PARTS: u64 = 10_000;
+
+/// Sidecar cache schema identifier
+const UPLOAD_CACHE_SCHEMA: &str = "barqly.vault.upload-metadata/1";
+
+/// Sidecar cache persisted next to the archive
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct UploadMetadataCache {
+    schema: String,
+    /// Archive size when the cache was written
+    file_size: u64,
+    /// Archive modification time (nanoseconds since the Unix epoch)
+    modified_nanos: u128,
+    /// Archive SHA-256 the cached entries belong to
+    sha256_hex: String,
+    /// One entry per part size that has been computed
+    entries: Vec<UploadMetadata>,
+}
+
+/// Size + modification time fingerprint used to detect archive changes cheaply
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct ArchiveFingerprint {
+    file_size: u64,
+    modified_nanos: u128,
+}
+
+impl ArchiveFingerprint {
+    fn read(path: &Path) -> Result<Self> {
+        let metadata = std::fs::metadata(path).map_err(|_| FileOpsError::FileNotFound {
+            path: path.to_path_buf(),
+        })?;
+
+        let modified_nanos = metadata
+            .modified()
+            .ok()
+            .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+            .map(|d| d.as_nanos())
+            .unwrap_or_default();
+
+        Ok(Self {
+            file_size: metadata.len(),
+            modified_nanos,
+        })
+    }
+}
+
+/// Generate the sidecar cache path for an archive (`Vault.age` → `Vault.age.upload.json`)
+pub fn generate_upload_metadata_path(archive_path: &Path) -> PathBuf {
+    let mut file_name = archive_path
+        .file_name()
+        .map(|n| n.to_os_string())
+        .unwrap_or_default();
+    file_name.push(".upload.json");
+    archive_path.with_file_name(file_name)
+}
+
+/// Compute upload metadata for an archive, using the sidecar cache when valid
+///
+/// # Arguments
+/// * `archive_path` - Path to the encrypted archive
+/// * `part_size_bytes` - Multipart part size in bytes (must be > 0)
+///
+/// # Errors
+/// - `FileOpsError::FileNotFound` if the archive does not exist
+/// - `FileOpsError::InvalidSelection` if the part size is zero or yields too many parts
+/// - `FileOpsError::HashCalculationFailed` if the archive cannot be read
+pub fn compute_upload_metadata(
+    archive_path: &Path,
+    part_size_bytes: u64,
+) -> Result<UploadMetadata> {
+    if part_size_bytes == 0 {
+        return Err(FileOpsError::InvalidSelection {
+            message: "Part size must be greater than zero".to_string(),
+        });
+    }
+
+    let fingerprint = ArchiveFingerprint::read(archive_path)?;
+    let part_count = fingerprint.file_size.div_ceil(part_size_bytes).max(1);
+    if part_count > MAX_UPLOAD_PARTS {
+        return Err(FileOpsError::InvalidSelection {
+            message: format!(
+                "Part size {part_size_bytes} bytes yields {part_count} parts (max {MAX_UPLOAD_PARTS})"
+            ),
+        });
+    }
+
+    let cache_path = generate_upload_metadata_path(archive_path);
+    let mut cache = load_cache(&cache_path).filter(|c| {
+        c.file_size == fingerprint.file_size && c.modified_nanos == fingerprint.modified_nanos
+    });
+
+    if let Some(cached) = cache.as_ref().and_then(|c| {
+        c.entries
+            .iter()
+            .find(|e| e.part_size_bytes.bytes() == part_size_bytes)
+    }) {
+        debug!(path = %archive_path.display(), part_size_bytes, "Upload metadata served from cache");
+        let mut metadata = cached.clone();
+        metadata.from_cache = true;
+        return Ok(metadata);
+    }
+
+    let metadata = hash_archive(archive_path, part_size_bytes)?;
+
+    // Only keep entries belonging to the same archive content
+    let cache = match cache.take() {
+        Some(mut existing) if existing.sha256_hex == metadata.sha256_hex => {
+            existing
+                .entries
+                .retain(|e| e.part_size_bytes.bytes() != part_size_bytes);
+            existing.entries.push(metadata.clone());
+            existing
+        }
+        _ => UploadMetadataCache {
+            schema: UPLOAD_CACHE_SCHEMA.to_string(),
+            file_size: fingerprint.file_size,
+            modified_nanos: fingerprint.modified_nanos,
+            sha256_hex: metadata.sha256_hex.clone(),
+            entries: vec![metadata.clone()],
+        },
+    };
+
+    // Cache write failures are non-fatal - the metadata itself is still valid
+    if let Err(e) = save_cache(&cache_path, &cache) {
+        warn!(path = %cache_path.display(), error = %e, "Failed to write upload metadata cache");
+    }
+
+    info!(
+        path = %archive_path.display(),
+        part_size_bytes,
+        parts = metadata.parts.len(),
+        "Computed upload metadata"
+    );
+
+    Ok(metadata)
+}
+
+/// Whole-file and per-part hashers, fed the archive in one pass
+struct ArchiveHasher {
+    part_size_bytes: u64,
+    sha256: Sha256,
+    md5: Md5,
+    part_hasher: Md5,
+    part_filled: u64,
+    offset: u64,
+    parts: Vec<UploadPartChecksum>,
+}
+
+impl ArchiveHasher {
+    fn new(part_size_bytes: u64) -> Self {
+        Self {
+            part_size_bytes,
+            sha256: Sha256::new(),
+            md5: Md5::new(),
+            part_hasher: Md5::new(),
+            part_filled: 0,
+            offset: 0,
+            parts: Vec::new(),
+        }
+    }
+
+    fn update(&mut self, chunk: &[u8]) {
+        self.sha256.update(chunk);
+        self.md5.update(chunk);
+
+        // Split the chunk across part boundaries
+        let mut remaining = chunk;
+        while !remaining.is_empty() {
+            let take =
+                (self.part_size_bytes - self.part_filled).min(remaining.len() as u64) as usize;
+            self.part_hasher.update(&remaining[..take]);
+            self.part_filled += take as u64;
+            remaining = &remaining[take..];
+
+            if self.part_filled == self.part_size_bytes {
+                self.finish_part();
+            }
+        }
+    }
+
+    fn finish_part(&mut self) {
+        self.parts.push(finish_part(
+            &mut self.part_hasher,
+            self.parts.len(),
+            self.offset,
+            self.part_filled,
+        ));
+        self.offset += self.part_filled;
+        self.part_filled = 0;
+    }
+}
+
+/// Read the archive once, feeding whole-file and per-part hashers
+fn hash_archive(archive_path: &Path, part_size_bytes: u64) -> Result<UploadMetadata> {
+    let (mut hasher, _) = read_for_hash(
+        archive_path,
+        || ArchiveHasher::new(part_size_bytes),
+        ArchiveHasher::update,
+    )?;
+
+    // Trailing partial part, or a single empty part for an empty file
+    if hasher.part_filled > 0 || hasher.parts.is_empty() {
+        hasher.finish_part();
+    }
+
+    let sha256 = hasher.sha256.finalize();
+    let md5 = hasher.md5.finalize();
+    let parts = hasher.parts;
+
+    Ok(UploadMetadata {
+        file_name: archive_path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default(),
+        file_size: ByteSize(hasher.offset),
+        sha256_hex: hex::encode(sha256),
+        sha256_base64: BASE64.encode(sha256),
+        md5_hex: hex::encode(md5),
+        md5_base64: BASE64.encode(md5),
+        part_size_bytes: ByteSize(part_size_bytes),
+        multipart_etag: composite_etag(&parts),
+        parts,
+        from_cache: false,
+    })
+}
+
+fn finish_part(hasher: &mut Md5, index: usize, offset: u64, size: u64) -> UploadPartChecksum {
+    let digest = hasher.finalize_reset();
+    UploadPartChecksum {
+        part_number: index as u32 + 1,
+        offset: ByteSize(offset),
+        size: ByteSize(size),
+        md5_hex: hex::encode(digest),
+        md5_base64: BASE64.encode(digest),
+    }
+}
+
+/// Compute the S3 composite multipart ETag: `hex(md5(concat(binary part md5s)))-<count>`
+pub fn composite_etag(parts: &[UploadPartChecksum]) -> String {
+    let mut hasher = Md5::new();
+    for part in parts {
+        // Part digests are produced by this module, so they are always valid hex
+        if let Ok(raw) = hex::decode(&part.md5_hex) {
+            hasher.update(raw);
+        }
+    }
+    format!("{}-{}", hex::encode(hasher.finalize()), parts.len())
+}
+
+fn load_cache(cache_path: &Path) -> Option<UploadMetadataCache> {
+    let content = std::fs::read_to_string(cache_path).ok()?;
+    serde_json::from_str::<UploadMetadataCache>(&content)
+        .ok()
+        .filter(|c| c.schema == UPLOAD_CACHE_SCHEMA)
+}
+
+fn save_cache(cache_path: &Path, cache: &UploadMetadataCache) -> Result<()> {
+    let json =
+        serde_json::to_string_pretty(cache).map_err(|e| FileOpsError::ManifestCreationFailed {
+            message: format!("Failed to serialize upload metadata cache: {e}"),
+        })?;
+
+    std::fs::write(cache_path, json).map_err(|e| FileOpsError::IoError {
+        message: format!("Failed to write upload metadata cache: {e}"),
+        source: e,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use tempfile::TempDir;
+
+    fn write_archive(dir: &TempDir, content: &[u8]) -> PathBuf {
+        let path = dir.path().join("vault.age");
+        std::fs::write(&path, content).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_composite_etag_known_fixture() {
+        let dir = TempDir::new().unwrap();
+        let path = write_archive(&dir, b"0123456789");
+
+        let metadata = compute_upload_metadata(&path, 4).unwrap();
+
+        let part_md5s: Vec<&str> = metadata.parts.iter().map(|p| p.md5_hex.as_str()).collect();
+        assert_eq!(
+            part_md5s,
+            vec![
+                "eb62f6b9306db575c2d596b1279627a4",
+                "6562c5c1f33db6e05a082a88cddab5ea",
+                "7647966b7343c29048673252e490f736",
+            ]
+        );
+        assert_eq!(
+            metadata.multipart_etag,
+            "61e3716e3a7767581863b67c4e785584-3"
+        );
+        assert_eq!(metadata.parts[2].offset, ByteSize(8));
+        assert_eq!(metadata.parts[2].size, ByteSize(2));
+    }
+
+    #[test]
+    fn test_whole_file_digests_and_base64() {
+        let dir = TempDir::new().unwrap();
+        let path = write_archive(&dir, b"0123456789");
+
+        let metadata = compute_upload_metadata(&path, 1024).unwrap();
+
+        assert_eq!(metadata.md5_hex, "781e5e245d69b566979b86e28d23f2c7");
+        assert_eq!(metadata.md5_base64, "eB5eJF1ptWaXm4bijSPyxw==");
+        assert_eq!(
+            metadata.sha256_hex,
+            "84d89877f0d4041efb6bf91a16f0248f2fd573e6af05c19f96bedb9f882f7882"
+        );
+        assert_eq!(
+            metadata.sha256_base64,
+            "hNiYd/DUBB77a/kaFvAkjy/Vc+avBcGflr7bn4gveII="
+        );
+        // Single part: composite ETag is md5(md5(file))-1
+        assert_eq!(
+            metadata.multipart_etag,
+            "8e938564cd1410f0ec1c1781466a6738-1"
+        );
+    }
+
+    #[test]
+    fn test_empty_archive_has_single_empty_part() {
+        let dir = TempDir::new().unwrap();
+        let path = write_archive(&dir, b"");
+
+        let metadata = compute_upload_metadata(&path, 4).unwrap();
+
+        assert_eq!(metadata.parts.len(), 1);
+        assert_eq!(metadata.parts[0].size, ByteSize(0));
+        assert_eq!(metadata.md5_hex, "d41d8cd98f00b204e9800998ecf8427e");
+    }
+
+    #[test]
+    fn test_cache_hit_and_invalidation_on_modification() {
+        let dir = TempDir::new().unwrap();
+        let path = write_archive(&dir, b"0123456789");
+
+        let first = compute_upload_metadata(&path, 4).unwrap();
+        assert!(!first.from_cache);
+        assert!(generate_upload_metadata_path(&path).exists());
+
+        let second = compute_upload_metadata(&path, 4).unwrap();
+        assert!(second.from_cache);
+        assert_eq!(second.multipart_etag, first.multipart_etag);
+
+        // Different part size is a cache miss but keeps the existing entry
+        let other = compute_upload_metadata(&path, 5).unwrap();
+        assert!(!other.from_cache);
+        assert!(compute_upload_metadata(&path, 4).unwrap().from_cache);
+
+        // Modify the archive - cache must be invalidated
+        let mut file = std::fs::OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .unwrap();
+        file.write_all(b"abc").unwrap();
+        drop(file);
+
+        let third = compute_upload_metadata(&path, 4).unwrap();
+        assert!(!third.from_cache);
+        assert_ne!(third.sha256_hex, first.sha256_hex);
+        assert_eq!(third.file_size, ByteSize(13));
+    }
+
+    #[test]
+    fn test_rejects_invalid_part_size() {
+        let dir = TempDir::new().unwrap();
+        let path = write_archive(&dir, b"0123456789");
+
+        assert!(compute_upload_metadata(&path, 0).is_err());
+        assert!(compute_upload_metadata(&dir.path().join("missing.age"), 4).is_err());
+    }
+
+    #[test]
+    fn test_generate_upload_metadata_path() {
+        let path = PathBuf::from("/path/to/Family-Vault.age");
+        assert_eq!(
+            generate_upload_metadata_path(&path),
+            PathBuf::from("/path/to/Family-Vault.age.upload.json")
+        );
+    }
+}
@@ -3,43 +3,72 @@
 //! This module provides shared utility functions used across
 //! different file operation modules.
 
+use super::content_type::sniff_content_type;
+use super::hash_read::read_for_hash;
+use super::locked_files::{
+    LockedFilePolicy, ReadOutcome, SkippedEntry, SkippedFile, read_with_policy, torn_sqlite_members,
+};
+use super::ownership::{FileOwnership, record_ownership};
+use super::raw_path::{RawPath, is_lossy_name};
+use super::resilient_source::ResilientSource;
 use super::{FileOpsError, Result, SelectionType};
-use crate::constants::IO_BUFFER_SIZE;
-use sha2::{Digest, Sha256};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256, Sha512};
 use std::fs::File;
-use std::io::Read;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+
+/// Digest algorithm recorded for a file entry
+///
+/// Serialized as its identifier (`sha256`, `sha512`). Entries written before
+/// the algorithm was recorded are SHA-256.
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize, specta::Type,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum HashAlgorithm {
+    #[default]
+    Sha256,
+    Sha512,
+}
+
+impl HashAlgorithm {
+    /// Identifier recorded in manifests
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Sha256 => "sha256",
+            Self::Sha512 => "sha512",
+        }
+    }
+}
+
+impl std::fmt::Display for HashAlgorithm {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
 
 /// Calculate SHA-256 hash of a file
 pub fn calculate_file_hash(path: &Path) -> Result<String> {
+    calculate_file_hash_with(path, HashAlgorithm::Sha256)
+}
+
+/// Calculate a file's hash with the given algorithm, hex encoded
+pub fn calculate_file_hash_with(path: &Path, algorithm: HashAlgorithm) -> Result<String> {
+    match algorithm {
+        HashAlgorithm::Sha256 => hash_file::<Sha256>(path),
+        HashAlgorithm::Sha512 => hash_file::<Sha512>(path),
+    }
+}
+
+fn hash_file<D: Digest>(path: &Path) -> Result<String> {
     debug_assert!(
         !path.as_os_str().is_empty(),
         "Path cannot be empty for hash calculation"
     );
 
-    let mut file = File::open(path).map_err(|_e| FileOpsError::FileNotFound {
-        path: path.to_path_buf(),
-    })?;
-
-    let mut hasher = Sha256::new();
-    let mut buffer = [0; IO_BUFFER_SIZE];
-
-    loop {
-        let n = file
-            .read(&mut buffer)
-            .map_err(|e| FileOpsError::HashCalculationFailed {
-                message: format!("Failed to read file: {e}"),
-            })?;
-
-        if n == 0 {
-            break;
-        }
-
-        hasher.update(&buffer[..n]);
-    }
-
-    let result = hasher.finalize();
-    Ok(hex::encode(result))
+    let (hasher, _) = read_for_hash(path, D::new, |hasher: &mut D, bytes| hasher.update(bytes))?;
+    Ok(hex::encode(hasher.finalize()))
 }
 
 /// Read archive file with size validation to prevent memory exhaustion
@@ -70,9 +99,46 @@ pub fn read_archive_with_size_check(path: &Path, max_size: u64) -> Result<Vec<u8
 /// Collected file metadata with hash
 #[derive(Debug, Clone)]
 pub struct CollectedFile {
+    /// Display form of the relative path
     pub relative_path: String,
+    /// The relative path exactly as named on disk
+    pub raw_path: RawPath,
+    /// `relative_path` isn't the true name
+    pub lossy_name: bool,
+    /// Where the file was read from
+    pub source_path: PathBuf,
     pub size: u64,
     pub sha256: String,
+    /// Owner of the source file (Unix only)
+    pub ownership: Option<FileOwnership>,
+    /// Type detected from the file's leading bytes, if it could be read
+    pub content_type: Option<String>,
+    /// The extension doesn't match the detected type
+    pub content_type_mismatch: bool,
+}
+
+/// Collected directory metadata; directories have no hash
+#[derive(Debug, Clone)]
+pub struct CollectedDirectory {
+    /// Display form of the relative path
+    pub relative_path: String,
+    /// The relative path exactly as named on disk
+    pub raw_path: RawPath,
+    /// `relative_path` isn't the true name
+    pub lossy_name: bool,
+    pub modified: Option<DateTime<Utc>>,
+    /// Mode bits (Unix only)
+    pub permissions: Option<u32>,
+}
+
+/// Files collected under a `LockedFilePolicy`
+#[derive(Debug, Clone, Default)]
+pub struct FileCollection {
+    pub files: Vec<CollectedFile>,
+    /// Unreadable files left out, in discovery order
+    pub skipped: Vec<SkippedFile>,
+    /// Subdirectories of a folder selection, including empty ones
+    pub directories: Vec<CollectedDirectory>,
 }
 
 /// Check if file should be excluded from encryption
@@ -121,6 +187,20 @@ pub fn should_exclude_file(path: &Path) -> bool {
     false
 }
 
+/// Subdirectories of `folder`, parents before children
+///
+/// Excluded (system or hidden) directories are pruned along with everything
+/// below them. The folder itself isn't yielded.
+pub fn walk_directories(folder: &Path) -> impl Iterator<Item = walkdir::DirEntry> {
+    walkdir::WalkDir::new(folder)
+        .follow_links(false)
+        .min_depth(1)
+        .into_iter()
+        .filter_entry(|entry| !entry.file_type().is_dir() || !should_exclude_file(entry.path()))
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_dir())
+}
+
 /// Collect all files from selection with metadata (handles files and folders)
 ///
 /// This is the canonical method for collecting file metadata with hashes.
@@ -137,9 +217,205 @@ pub fn should_exclude_file(path: &Path) -> bool {
 pub fn collect_files_with_metadata(
     file_paths: &[String],
     selection_type: SelectionType,
-    _base_path: Option<&str>,
+    base_path: Option<&str>,
 ) -> Result<Vec<CollectedFile>> {
-    let mut collected = Vec::new();
+    collect_files_with_policy(
+        file_paths,
+        selection_type,
+        base_path,
+        LockedFilePolicy::Fail,
+        &[],
+    )
+    .map(|collection| collection.files)
+}
+
+/// Collect files like `collect_files_with_metadata`, handling unreadable
+/// (locked or permission-denied) files according to `policy`
+///
+/// Skipped files are returned alongside the collected ones. When one file of
+/// a SQLite database trio (`db`, `db-wal`, `db-shm`) is skipped, the rest of
+/// the trio is skipped too.
+///
+/// Paths in `excluded`, and everything below them, aren't collected at all.
+pub fn collect_files_with_policy(
+    file_paths: &[String],
+    selection_type: SelectionType,
+    _base_path: Option<&str>,
+    policy: LockedFilePolicy,
+    excluded: &[PathBuf],
+) -> Result<FileCollection> {
+    let directories = collect_directories(file_paths, &selection_type, excluded)?;
+    let mut files: Vec<(PathBuf, CollectedFile)> = Vec::new();
+    let mut skipped = Vec::new();
+
+    for (source_path, relative_path) in collection_candidates(file_paths, selection_type, excluded)?
+    {
+        match read_with_policy(policy, || collect_file(&source_path, &relative_path))? {
+            ReadOutcome::Read(file) => files.push((source_path, file)),
+            ReadOutcome::Skipped(reason) => {
+                tracing::warn!(
+                    path = %source_path.display(),
+                    reason = %reason,
+                    "Skipping unreadable source file"
+                );
+                skipped.push(SkippedFile {
+                    source_path,
+                    entry: SkippedEntry {
+                        path: relative_path.to_string_lossy().into_owned(),
+                        reason,
+                    },
+                });
+            }
+        }
+    }
+
+    let mut collection = finish_collection(files, skipped);
+    collection.directories = directories;
+    Ok(collection)
+}
+
+/// Collect files like `collect_files_with_policy`, reading through a
+/// `ResilientSource` (resilient-source mode for slow or flaky media)
+///
+/// Transient read errors are retried before `policy` sees them, and files are
+/// hashed with the parallelism the source settles on after sampling
+/// throughput. Retry and throughput details accumulate in `source.report()`.
+pub fn collect_files_resilient(
+    file_paths: &[String],
+    selection_type: SelectionType,
+    _base_path: Option<&str>,
+    policy: LockedFilePolicy,
+    source: &ResilientSource,
+    excluded: &[PathBuf],
+) -> Result<FileCollection> {
+    let directories = collect_directories(file_paths, &selection_type, excluded)?;
+    let candidates = collection_candidates(file_paths, selection_type, excluded)?;
+    let outcomes = source.map_files(&candidates, |(source_path, relative_path)| {
+        read_with_policy(policy, || {
+            collect_file_resilient(source, source_path, relative_path)
+        })
+    });
+
+    let mut files: Vec<(PathBuf, CollectedFile)> = Vec::new();
+    let mut skipped = Vec::new();
+    for ((source_path, relative_path), outcome) in candidates.into_iter().zip(outcomes) {
+        match outcome? {
+            ReadOutcome::Read(file) => files.push((source_path, file)),
+            ReadOutcome::Skipped(reason) => {
+                tracing::warn!(
+                    path = %source_path.display(),
+                    reason = %reason,
+                    "Skipping unreadable source file"
+                );
+                skipped.push(SkippedFile {
+                    source_path,
+                    entry: SkippedEntry {
+                        path: relative_path.to_string_lossy().into_owned(),
+                        reason,
+                    },
+                });
+            }
+        }
+    }
+
+    let mut collection = finish_collection(files, skipped);
+    collection.directories = directories;
+    Ok(collection)
+}
+
+/// Drop the rest of torn SQLite trios and split off the source paths
+fn finish_collection(
+    mut files: Vec<(PathBuf, CollectedFile)>,
+    mut skipped: Vec<SkippedFile>,
+) -> FileCollection {
+    if !skipped.is_empty() {
+        let included: Vec<PathBuf> = files.iter().map(|(path, _)| path.clone()).collect();
+        let skipped_paths: Vec<PathBuf> = skipped.iter().map(|s| s.source_path.clone()).collect();
+
+        for (path, reason) in torn_sqlite_members(&included, &skipped_paths) {
+            if let Some(index) = files.iter().position(|(source, _)| *source == path) {
+                let (source_path, file) = files.remove(index);
+                skipped.push(SkippedFile {
+                    source_path,
+                    entry: SkippedEntry {
+                        path: file.relative_path,
+                        reason,
+                    },
+                });
+            }
+        }
+    }
+
+    FileCollection {
+        files: files.into_iter().map(|(_, file)| file).collect(),
+        skipped,
+        directories: Vec::new(),
+    }
+}
+
+/// Directories of a single-folder selection, relative to the folder
+///
+/// Recorded whether or not they still hold files, so a directory whose
+/// files were all skipped or excluded is restored empty.
+fn collect_directories(
+    file_paths: &[String],
+    selection_type: &SelectionType,
+    excluded: &[PathBuf],
+) -> Result<Vec<CollectedDirectory>> {
+    if *selection_type != SelectionType::Folder || file_paths.len() != 1 {
+        return Ok(Vec::new());
+    }
+
+    let folder = Path::new(&file_paths[0]);
+    let mut directories = Vec::new();
+    for entry in walk_directories(folder).filter(|e| !is_excluded(e.path(), excluded)) {
+        let relative_path = entry.path().strip_prefix(folder).map_err(|e| {
+            FileOpsError::CrossPlatformPathError {
+                message: format!("Failed to get relative path: {}", e),
+            }
+        })?;
+        let metadata = entry.metadata().ok();
+
+        directories.push(CollectedDirectory {
+            relative_path: relative_path.to_string_lossy().into_owned(),
+            raw_path: RawPath::from_relative(relative_path),
+            lossy_name: is_lossy_name(relative_path),
+            modified: metadata
+                .as_ref()
+                .and_then(|m| m.modified().ok())
+                .map(DateTime::from),
+            permissions: metadata.as_ref().and_then(directory_mode),
+        });
+    }
+
+    Ok(directories)
+}
+
+#[cfg(unix)]
+fn directory_mode(metadata: &std::fs::Metadata) -> Option<u32> {
+    use std::os::unix::fs::PermissionsExt;
+    Some(metadata.permissions().mode() & 0o7777)
+}
+
+#[cfg(not(unix))]
+fn directory_mode(_metadata: &std::fs::Metadata) -> Option<u32> {
+    None
+}
+
+/// Whether `path` is one of `excluded` or below one
+fn is_excluded(path: &Path, excluded: &[PathBuf]) -> bool {
+    excluded.iter().any(|root| path.starts_with(root))
+}
+
+/// Resolve the selection into (source path, relative path) pairs
+///
+/// Paths in `excluded`, and everything below them, are left out.
+fn collection_candidates(
+    file_paths: &[String],
+    selection_type: SelectionType,
+    excluded: &[PathBuf],
+) -> Result<Vec<(PathBuf, PathBuf)>> {
+    let mut candidates = Vec::new();
 
     match selection_type {
         SelectionType::Folder if file_paths.len() == 1 => {
@@ -156,6 +432,7 @@ pub fn collect_files_with_metadata(
             for entry in walkdir::WalkDir::new(folder)
                 .follow_links(false)
                 .into_iter()
+                .filter_entry(|e| !is_excluded(e.path(), excluded))
                 .filter_map(|e| e.ok())
             {
                 if entry.file_type().is_file() {
@@ -168,27 +445,13 @@ pub fn collect_files_with_metadata(
                     }
 
                     // Calculate relative path from folder root
-                    let relative_path = file_path
-                        .strip_prefix(folder)
-                        .map_err(|e| FileOpsError::CrossPlatformPathError {
+                    let relative_path = file_path.strip_prefix(folder).map_err(|e| {
+                        FileOpsError::CrossPlatformPathError {
                             message: format!("Failed to get relative path: {}", e),
-                        })?
-                        .to_string_lossy()
-                        .to_string();
-
-                    let metadata =
-                        std::fs::metadata(file_path).map_err(|e| FileOpsError::IoError {
-                            message: format!("Failed to read metadata: {}", e),
-                            source: e,
-                        })?;
-
-                    let hash = calculate_file_hash(file_path)?;
+                        }
+                    })?;
 
-                    collected.push(CollectedFile {
-                        relative_path,
-                        size: metadata.len(),
-                        sha256: hash,
-                    });
+                    candidates.push((file_path.to_path_buf(), relative_path.to_path_buf()));
                 }
             }
         }
@@ -202,36 +465,92 @@ pub fn collect_files_with_metadata(
                     continue;
                 }
 
+                if is_excluded(path, excluded) {
+                    tracing::debug!(path = %path_str, "Excluded from the selection, skipping");
+                    continue;
+                }
+
                 // Skip directories in Files mode
                 if path.is_dir() {
                     tracing::warn!(path = %path_str, "Directory in Files mode, skipping");
                     continue;
                 }
 
-                let metadata = std::fs::metadata(path).map_err(|e| FileOpsError::IoError {
-                    message: format!("Failed to read metadata: {}", e),
-                    source: e,
-                })?;
-
-                let hash = calculate_file_hash(path)?;
-
-                let relative_path = path
-                    .file_name()
-                    .ok_or_else(|| FileOpsError::PathValidationFailed {
-                        path: path.to_path_buf(),
-                        reason: "Invalid file path".to_string(),
-                    })?
-                    .to_string_lossy()
-                    .to_string();
-
-                collected.push(CollectedFile {
-                    relative_path,
-                    size: metadata.len(),
-                    sha256: hash,
-                });
+                let relative_path =
+                    path.file_name()
+                        .ok_or_else(|| FileOpsError::PathValidationFailed {
+                            path: path.to_path_buf(),
+                            reason: "Invalid file path".to_string(),
+                        })?;
+
+                candidates.push((path.to_path_buf(), PathBuf::from(relative_path)));
             }
         }
     }
 
-    Ok(collected)
+    Ok(candidates)
+}
+
+/// Read metadata and hash for a single file
+fn collect_file(path: &Path, relative_path: &Path) -> Result<CollectedFile> {
+    let metadata = std::fs::metadata(path).map_err(|e| FileOpsError::IoError {
+        message: format!("Failed to read metadata: {}", e),
+        source: e,
+    })?;
+
+    // Open explicitly so a lock or permission problem surfaces with the OS
+    // reason instead of the generic not-found error from hashing
+    File::open(path).map_err(|e| match e.kind() {
+        std::io::ErrorKind::NotFound => FileOpsError::FileNotFound {
+            path: path.to_path_buf(),
+        },
+        _ => FileOpsError::IoError {
+            message: format!("Failed to open file: {}", e),
+            source: e,
+        },
+    })?;
+
+    let hash = calculate_file_hash(path)?;
+
+    let sniffed = sniff_content_type(path);
+
+    Ok(CollectedFile {
+        relative_path: relative_path.to_string_lossy().into_owned(),
+        raw_path: RawPath::from_relative(relative_path),
+        lossy_name: is_lossy_name(relative_path),
+        source_path: path.to_path_buf(),
+        size: metadata.len(),
+        sha256: hash,
+        ownership: record_ownership(&metadata),
+        content_type_mismatch: sniffed.as_ref().is_some_and(|info| info.extension_mismatch),
+        content_type: sniffed.map(|info| info.content_type),
+    })
+}
+
+/// Read metadata and hash for a single file through a `ResilientSource`
+fn collect_file_resilient(
+    source: &ResilientSource,
+    path: &Path,
+    relative_path: &Path,
+) -> Result<CollectedFile> {
+    let metadata = std::fs::metadata(path).map_err(|e| FileOpsError::IoError {
+        message: format!("Failed to read metadata: {}", e),
+        source: e,
+    })?;
+
+    let (size, hash) = source.hash_file(path, &relative_path.to_string_lossy())?;
+
+    let sniffed = sniff_content_type(path);
+
+    Ok(CollectedFile {
+        relative_path: relative_path.to_string_lossy().into_owned(),
+        raw_path: RawPath::from_relative(relative_path),
+        lossy_name: is_lossy_name(relative_path),
+        source_path: path.to_path_buf(),
+        size,
+        sha256: hash,
+        ownership: record_ownership(&metadata),
+        content_type_mismatch: sniffed.as_ref().is_some_and(|info| info.extension_mismatch),
+        content_type: sniffed.map(|info| info.content_type),
+    })
 }
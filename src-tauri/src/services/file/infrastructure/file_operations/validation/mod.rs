@@ -1,13 +1,25 @@
 //! Validation module for file operations
 
 pub mod content_validation;
+pub mod overlap;
+pub mod path_limits;
 pub mod path_validation;
+pub mod safety_limits;
 pub mod size_validation;
 
 // Re-export commonly used functions
+pub use overlap::{
+    OverlapExclusions, ProtectedKind, ProtectedPath, SelectionOverlap, resolve_path,
+    resolve_selection_overlaps,
+};
+pub use path_limits::{
+    PathLimitStrategy, PathLimitViolation, PathLimits, TargetPlatform, audit_entry_paths,
+    audit_portability,
+};
 pub use path_validation::{
     contains_traversal_attempt, get_relative_path, normalize_path,
     validate_and_create_output_directory, validate_archive_path, validate_paths,
     validate_single_path,
 };
+pub use safety_limits::{ArchiveSafetyLimits, SafetyLimit, SafetyTally, prescan_archive};
 pub use size_validation::validate_file_size;
@@ -0,0 +1,246 @@
+//! Selections that overlap the app's own storage
+//!
+//! Selecting a home folder also selects the app data directory, the vaults
+//! directory and the archive being written, so the backup would grow with
+//! every run and could end up reading itself. Paths are resolved through
+//! symlinks before comparing, then the overlapping subtrees are either left
+//! out of the selection or refused.
+
+use super::super::{FileOpsError, Result};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Kind of location the app writes to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+#[serde(rename_all = "snake_case")]
+pub enum ProtectedKind {
+    /// Key registry, manifests, settings and logs
+    AppData,
+    /// Where encrypted vault archives are stored
+    VaultStorage,
+    /// Temporary staging of archives being built
+    Staging,
+    /// The archive this operation writes
+    Output,
+}
+
+impl ProtectedKind {
+    /// Whether selecting something inside the location is refused too
+    ///
+    /// Staging lives in the system temp directory, which may hold the user's
+    /// own files; only selections containing it are a problem.
+    fn guards_contents(self) -> bool {
+        !matches!(self, Self::Staging)
+    }
+}
+
+/// A location that must not be archived into its own backup
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProtectedPath {
+    pub kind: ProtectedKind,
+    pub path: PathBuf,
+}
+
+impl ProtectedPath {
+    pub fn new(kind: ProtectedKind, path: impl Into<PathBuf>) -> Self {
+        Self {
+            kind,
+            path: path.into(),
+        }
+    }
+}
+
+/// Part of a selection left out because it overlaps app storage
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+pub struct SelectionOverlap {
+    /// Path as it appears under the selection
+    pub path: String,
+    pub kind: ProtectedKind,
+}
+
+/// Paths left out of a selection
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OverlapExclusions {
+    /// Subtrees under the selected paths, or whole selected paths
+    pub excluded: Vec<PathBuf>,
+    pub overlaps: Vec<SelectionOverlap>,
+}
+
+impl OverlapExclusions {
+    pub fn is_empty(&self) -> bool {
+        self.excluded.is_empty()
+    }
+
+    fn push(&mut self, path: PathBuf, kind: ProtectedKind) {
+        // A subtree already inside an excluded one adds nothing
+        if self.excluded.iter().any(|p| path.starts_with(p)) {
+            return;
+        }
+        self.excluded.retain(|p| !p.starts_with(&path));
+        self.overlaps
+            .retain(|o| !Path::new(&o.path).starts_with(&path));
+        self.overlaps.push(SelectionOverlap {
+            path: path.to_string_lossy().to_string(),
+            kind,
+        });
+        self.excluded.push(path);
+    }
+}
+
+/// Find where `selected` overlaps `protected`
+///
+/// With `strict` any overlap is an error; otherwise the overlapping subtrees
+/// are returned to be left out. Leaving out every selected path is always an
+/// error, since nothing of the user's would be archived.
+pub fn resolve_selection_overlaps(
+    selected: &[PathBuf],
+    protected: &[ProtectedPath],
+    strict: bool,
+) -> Result<OverlapExclusions> {
+    let protected: Vec<(ProtectedKind, PathBuf)> = protected
+        .iter()
+        .map(|p| (p.kind, resolve_path(&p.path)))
+        .collect();
+
+    let mut exclusions = OverlapExclusions::default();
+    let mut fully_excluded = 0usize;
+    for raw in selected {
+        let resolved = resolve_path(raw);
+
+        if let Some((kind, _)) = protected
+            .iter()
+            .find(|(kind, path)| kind.guards_contents() && resolved.starts_with(path))
+        {
+            fully_excluded += 1;
+            exclusions.push(raw.clone(), *kind);
+            continue;
+        }
+
+        for (kind, path) in &protected {
+            if let Ok(relative) = path.strip_prefix(&resolved)
+                && !relative.as_os_str().is_empty()
+            {
+                exclusions.push(raw.join(relative), *kind);
+            }
+        }
+    }
+
+    if exclusions.is_empty() {
+        return Ok(exclusions);
+    }
+    if strict || fully_excluded == selected.len() {
+        return Err(FileOpsError::SelectionOverlapsAppData {
+            paths: exclusions.excluded,
+        });
+    }
+
+    tracing::warn!(
+        excluded = exclusions.excluded.len(),
+        "Leaving app storage out of the selection"
+    );
+    Ok(exclusions)
+}
+
+/// Canonical form of `path`, resolving symlinks
+///
+/// Paths that don't exist yet (an archive about to be written) are resolved
+/// through their nearest existing ancestor.
+pub fn resolve_path(path: &Path) -> PathBuf {
+    let mut missing = Vec::new();
+    let mut current = path;
+    loop {
+        if let Ok(resolved) = std::fs::canonicalize(current) {
+            return missing
+                .iter()
+                .rev()
+                .fold(resolved, |acc: PathBuf, part| acc.join(part));
+        }
+        match (current.parent(), current.file_name()) {
+            (Some(parent), Some(name)) => {
+                missing.push(name.to_os_string());
+                current = parent;
+            }
+            _ => return path.to_path_buf(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn layout() -> (TempDir, PathBuf, PathBuf) {
+        let temp = TempDir::new().unwrap();
+        let home = temp.path().join("home");
+        let data = home.join("appdata").join("com.barqly.vault");
+        fs::create_dir_all(data.join("keys")).unwrap();
+        fs::create_dir_all(home.join("Documents")).unwrap();
+        fs::write(home.join("Documents/notes.txt"), b"notes").unwrap();
+        (temp, home, data)
+    }
+
+    #[test]
+    fn test_parent_of_data_dir_excludes_it() {
+        let (_temp, home, data) = layout();
+        let protected = [ProtectedPath::new(ProtectedKind::AppData, &data)];
+
+        let exclusions = resolve_selection_overlaps(&[home.clone()], &protected, false).unwrap();
+        assert_eq!(
+            exclusions.excluded,
+            vec![home.join("appdata").join("com.barqly.vault")]
+        );
+        assert_eq!(exclusions.overlaps[0].kind, ProtectedKind::AppData);
+
+        let strict = resolve_selection_overlaps(&[home], &protected, true);
+        assert!(matches!(
+            strict,
+            Err(FileOpsError::SelectionOverlapsAppData { paths }) if paths.len() == 1
+        ));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_symlink_into_data_dir_is_resolved() {
+        let (_temp, home, data) = layout();
+        let link = home.join("Documents").join("keys-link");
+        std::os::unix::fs::symlink(data.join("keys"), &link).unwrap();
+        let protected = [ProtectedPath::new(ProtectedKind::AppData, &data)];
+
+        let selection = [home.join("Documents").join("notes.txt"), link.clone()];
+        let exclusions = resolve_selection_overlaps(&selection, &protected, false).unwrap();
+        assert_eq!(exclusions.excluded, vec![link.clone()]);
+
+        // Nothing left to archive
+        assert!(resolve_selection_overlaps(&[link], &protected, false).is_err());
+    }
+
+    #[test]
+    fn test_output_inside_selection() {
+        let (_temp, home, _data) = layout();
+        let output = home
+            .join("Documents")
+            .join("Barqly-Vaults")
+            .join("vault.age");
+        let protected = [
+            ProtectedPath::new(ProtectedKind::Output, &output),
+            ProtectedPath::new(ProtectedKind::Staging, home.join("Documents")),
+        ];
+
+        let exclusions =
+            resolve_selection_overlaps(&[home.join("Documents")], &protected, false).unwrap();
+        assert_eq!(exclusions.excluded, vec![output]);
+        assert_eq!(exclusions.overlaps[0].kind, ProtectedKind::Output);
+    }
+
+    #[test]
+    fn test_unrelated_selection_is_untouched() {
+        let (_temp, home, data) = layout();
+        let protected = [ProtectedPath::new(ProtectedKind::AppData, &data)];
+
+        let exclusions =
+            resolve_selection_overlaps(&[home.join("Documents")], &protected, true).unwrap();
+        assert!(exclusions.is_empty());
+    }
+}
@@ -0,0 +1,493 @@
+//! Platform path length limits
+//!
+//! Archives created on one platform can contain paths that cannot be written on
+//! another: Windows caps full paths at 260 UTF-16 units, and macOS measures
+//! filenames in bytes after NFD normalization. This module measures paths the
+//! way each platform does, audits archive entries before extraction, and plans
+//! shortened names for the `ShortenWithHashSuffix` strategy.
+
+use crate::constants::*;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fmt;
+use std::path::{Component, Path, PathBuf};
+use unicode_normalization::UnicodeNormalization;
+
+/// Maximum extension length kept intact when shortening a filename
+const MAX_PRESERVED_EXTENSION: usize = 16;
+
+/// Platform whose path rules are being applied
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+pub enum TargetPlatform {
+    Windows,
+    MacOs,
+    Linux,
+}
+
+impl TargetPlatform {
+    /// All platforms a vault may be restored on
+    pub const ALL: [TargetPlatform; 3] = [
+        TargetPlatform::Windows,
+        TargetPlatform::MacOs,
+        TargetPlatform::Linux,
+    ];
+
+    /// Platform this build is running on
+    pub fn current() -> Self {
+        if cfg!(target_os = "windows") {
+            TargetPlatform::Windows
+        } else if cfg!(target_os = "macos") {
+            TargetPlatform::MacOs
+        } else {
+            TargetPlatform::Linux
+        }
+    }
+
+    /// Measure a path or component in this platform's native units
+    ///
+    /// Windows counts UTF-16 code units, macOS counts UTF-8 bytes after NFD
+    /// normalization, and Linux counts raw UTF-8 bytes.
+    pub fn measure(&self, value: &str) -> usize {
+        match self {
+            TargetPlatform::Windows => value.encode_utf16().count(),
+            TargetPlatform::MacOs => value.nfd().map(char::len_utf8).sum(),
+            TargetPlatform::Linux => value.len(),
+        }
+    }
+}
+
+impl fmt::Display for TargetPlatform {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TargetPlatform::Windows => write!(f, "Windows"),
+            TargetPlatform::MacOs => write!(f, "macOS"),
+            TargetPlatform::Linux => write!(f, "Linux"),
+        }
+    }
+}
+
+/// How extraction handles entries that exceed platform path limits
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+pub enum PathLimitStrategy {
+    /// Abort before writing anything and report every offending entry
+    #[default]
+    Fail,
+    /// Truncate offending names and append a short content hash
+    ShortenWithHashSuffix,
+    /// Use the `\\?\` extended-length prefix (Windows only)
+    UseExtendedPaths,
+}
+
+/// Path length limits for a platform
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PathLimits {
+    pub platform: TargetPlatform,
+    /// Maximum full path length, including the terminating NUL
+    pub max_path: usize,
+    /// Maximum length of a single path component
+    pub max_component: usize,
+}
+
+impl PathLimits {
+    /// Default limits for a platform
+    pub fn for_platform(platform: TargetPlatform) -> Self {
+        let max_path = match platform {
+            TargetPlatform::Windows => WINDOWS_MAX_PATH,
+            TargetPlatform::MacOs => MACOS_MAX_PATH,
+            TargetPlatform::Linux => LINUX_MAX_PATH,
+        };
+
+        Self {
+            platform,
+            max_path,
+            max_component: MAX_PATH_COMPONENT,
+        }
+    }
+
+    /// Limits when writing through Windows extended-length (`\\?\`) paths
+    pub fn windows_extended() -> Self {
+        Self {
+            platform: TargetPlatform::Windows,
+            max_path: WINDOWS_EXTENDED_MAX_PATH,
+            max_component: MAX_PATH_COMPONENT,
+        }
+    }
+
+    /// Limits for the platform this build is running on
+    pub fn current() -> Self {
+        Self::for_platform(TargetPlatform::current())
+    }
+
+    /// Check an entry's relative path given the measured length of the output directory
+    pub fn check_relative(&self, base_len: usize, entry: &Path) -> Option<PathLimitViolation> {
+        let components = normal_components(entry);
+        if components.is_empty() {
+            return None;
+        }
+
+        // One separator between the base and each component
+        let path_length = base_len
+            + components
+                .iter()
+                .map(|c| self.platform.measure(c) + 1)
+                .sum::<usize>();
+        let longest_component = components
+            .iter()
+            .map(|c| self.platform.measure(c))
+            .max()
+            .unwrap_or(0);
+
+        // max_path includes the terminating NUL
+        if path_length < self.max_path && longest_component <= self.max_component {
+            return None;
+        }
+
+        Some(PathLimitViolation {
+            entry_path: entry.to_path_buf(),
+            platform: self.platform,
+            path_length,
+            max_path_length: self.max_path - 1,
+            longest_component,
+            max_component_length: self.max_component,
+        })
+    }
+
+    /// Check where an entry would land when extracted into `output_dir`
+    pub fn check_entry(&self, output_dir: &Path, entry: &Path) -> Option<PathLimitViolation> {
+        self.check_relative(self.measure_path(output_dir), entry)
+    }
+
+    /// Measure a full path in this platform's units
+    pub fn measure_path(&self, path: &Path) -> usize {
+        self.platform.measure(&path.to_string_lossy())
+    }
+}
+
+/// An archive entry that cannot be written under a platform's path limits
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PathLimitViolation {
+    /// Entry path as stored in the archive
+    pub entry_path: PathBuf,
+    pub platform: TargetPlatform,
+    /// Final on-disk path length
+    pub path_length: usize,
+    pub max_path_length: usize,
+    /// Length of the longest component in the entry
+    pub longest_component: usize,
+    pub max_component_length: usize,
+}
+
+impl fmt::Display for PathLimitViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} (path {}/{}, longest name {}/{} on {})",
+            self.entry_path.display(),
+            self.path_length,
+            self.max_path_length,
+            self.longest_component,
+            self.max_component_length,
+            self.platform
+        )
+    }
+}
+
+/// Audit archive entries against limits for extraction into `output_dir`
+pub fn audit_entry_paths(
+    limits: &PathLimits,
+    output_dir: &Path,
+    entries: &[PathBuf],
+) -> Vec<PathLimitViolation> {
+    let base_len = limits.measure_path(output_dir);
+    entries
+        .iter()
+        .filter_map(|entry| limits.check_relative(base_len, entry))
+        .collect()
+}
+
+/// Check archive-relative paths against every platform's default limits
+///
+/// The output directory on the restoring machine is unknown, so a typical
+/// recovery location length (`PORTABLE_OUTPUT_DIR_BUDGET`) is assumed.
+pub fn audit_portability(relative_paths: &[PathBuf]) -> Vec<PathLimitViolation> {
+    TargetPlatform::ALL
+        .iter()
+        .flat_map(|platform| {
+            let limits = PathLimits::for_platform(*platform);
+            relative_paths
+                .iter()
+                .filter_map(move |path| limits.check_relative(PORTABLE_OUTPUT_DIR_BUDGET, path))
+        })
+        .collect()
+}
+
+/// Format violations as a single line for error messages
+pub fn summarize_path_violations(violations: &[PathLimitViolation]) -> String {
+    violations
+        .iter()
+        .map(|v| v.to_string())
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+/// Shorten directory components that exceed the component limit
+///
+/// Each shortened directory gets a hash of its original archive prefix, so all
+/// files from the same directory keep landing in the same shortened directory.
+pub fn shorten_parent_path(limits: &PathLimits, entry: &Path) -> Option<PathBuf> {
+    let components = normal_components(entry);
+    let (_, directories) = components.split_last()?;
+
+    let mut original_prefix = String::new();
+    let mut shortened = PathBuf::new();
+
+    for directory in directories {
+        if !original_prefix.is_empty() {
+            original_prefix.push('/');
+        }
+        original_prefix.push_str(directory);
+
+        if limits.platform.measure(directory) <= limits.max_component {
+            shortened.push(directory);
+        } else {
+            let tag = short_hash(original_prefix.as_bytes());
+            shortened.push(shorten_name(
+                limits.platform,
+                directory,
+                limits.max_component,
+                &tag,
+            )?);
+        }
+    }
+
+    Some(shortened)
+}
+
+/// Space left for the file name once the output directory and parent are placed
+pub fn file_name_budget(limits: &PathLimits, base_len: usize, parent: &Path) -> usize {
+    let parent_len = normal_components(parent)
+        .iter()
+        .map(|c| limits.platform.measure(c) + 1)
+        .sum::<usize>();
+
+    // Separator before the file name plus the terminating NUL
+    let used = base_len + parent_len + 2;
+    limits
+        .max_path
+        .saturating_sub(used)
+        .min(limits.max_component)
+}
+
+/// Truncate a name to fit `max_len` and append `~<tag>` before the extension
+///
+/// Returns `None` when not even one character of the original stem fits.
+pub fn shorten_name(
+    platform: TargetPlatform,
+    name: &str,
+    max_len: usize,
+    tag: &str,
+) -> Option<String> {
+    let (stem, extension) = match name.rfind('.') {
+        Some(idx) if idx > 0 && name.len() - idx <= MAX_PRESERVED_EXTENSION => {
+            (&name[..idx], &name[idx..])
+        }
+        _ => (name, ""),
+    };
+
+    let suffix = format!("~{tag}{extension}");
+    let stem_budget = max_len.checked_sub(platform.measure(&suffix))?;
+
+    let mut prefix = String::new();
+    let mut prefix_len = 0;
+    for ch in stem.chars() {
+        let ch_len = platform.measure(ch.encode_utf8(&mut [0; 4]));
+        if prefix_len + ch_len > stem_budget {
+            break;
+        }
+        prefix.push(ch);
+        prefix_len += ch_len;
+    }
+
+    if prefix.is_empty() {
+        return None;
+    }
+
+    Some(format!("{prefix}{suffix}"))
+}
+
+/// Short hex tag used for shortened names
+pub fn short_hash(data: &[u8]) -> String {
+    let digest = hex::encode(Sha256::digest(data));
+    digest[..SHORTENED_NAME_HASH_LEN].to_string()
+}
+
+/// Rebuild an archive path from its normal components using native separators
+pub fn native_relative_path(entry: &Path) -> PathBuf {
+    normal_components(entry).iter().collect()
+}
+
+fn normal_components(path: &Path) -> Vec<String> {
+    path.components()
+        .filter_map(|c| match c {
+            Component::Normal(part) => Some(part.to_string_lossy().to_string()),
+            _ => None,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Build a relative path of `depth` directories with `dir_len`-char names
+    fn deep_path(depth: usize, dir_len: usize, file_name: &str) -> PathBuf {
+        let mut path = PathBuf::new();
+        for i in 0..depth {
+            let name = format!("{}{}", i % 10, "d".repeat(dir_len - 1));
+            path.push(name);
+        }
+        path.push(file_name);
+        path
+    }
+
+    #[test]
+    fn test_windows_max_path_boundary() {
+        let limits = PathLimits::for_platform(TargetPlatform::Windows);
+        let base = "C:\\out"; // 6 units
+
+        // 6 + (1 + 19) * 12 + (1 + 12) = 259 -> fits (260 with NUL)
+        let at_limit = deep_path(12, 19, "abcdefgh.txt");
+        assert!(limits.check_relative(base.len(), &at_limit).is_none());
+
+        let over_limit = deep_path(12, 19, "abcdefghi.txt");
+        let violation = limits.check_relative(base.len(), &over_limit).unwrap();
+        assert_eq!(violation.path_length, 260);
+        assert_eq!(violation.max_path_length, 259);
+    }
+
+    #[test]
+    fn test_linux_max_path_boundary() {
+        let limits = PathLimits::for_platform(TargetPlatform::Linux);
+
+        // base 4 + (1 + 99) * 40 + (1 + 90) = 4095 -> fits (4096 with NUL)
+        let at_limit = deep_path(40, 99, &"f".repeat(90));
+        assert!(limits.check_relative(4, &at_limit).is_none());
+
+        let over_limit = deep_path(40, 99, &"f".repeat(91));
+        assert!(limits.check_relative(4, &over_limit).is_some());
+
+        // Same tree is far over the Windows limit
+        let windows = PathLimits::for_platform(TargetPlatform::Windows);
+        assert!(windows.check_relative(4, &at_limit).is_some());
+    }
+
+    #[test]
+    fn test_component_limit() {
+        let limits = PathLimits::for_platform(TargetPlatform::Linux);
+        assert!(
+            limits
+                .check_relative(4, Path::new(&"a".repeat(255)))
+                .is_none()
+        );
+
+        let violation = limits
+            .check_relative(4, Path::new(&"a".repeat(256)))
+            .unwrap();
+        assert_eq!(violation.longest_component, 256);
+    }
+
+    #[test]
+    fn test_macos_measures_nfd_bytes() {
+        // U+00E9 is 2 bytes in NFC but 3 bytes (e + U+0301) after NFD
+        let name = "\u{e9}".repeat(100);
+        let path = PathBuf::from(&name);
+
+        assert_eq!(TargetPlatform::Linux.measure(&name), 200);
+        assert_eq!(TargetPlatform::MacOs.measure(&name), 300);
+        assert_eq!(TargetPlatform::Windows.measure(&name), 100);
+
+        assert!(
+            PathLimits::for_platform(TargetPlatform::Linux)
+                .check_relative(4, &path)
+                .is_none()
+        );
+        assert!(
+            PathLimits::for_platform(TargetPlatform::MacOs)
+                .check_relative(4, &path)
+                .is_some()
+        );
+    }
+
+    #[test]
+    fn test_windows_extended_limits() {
+        let long = deep_path(20, 50, "file.txt");
+        assert!(
+            PathLimits::for_platform(TargetPlatform::Windows)
+                .check_relative(10, &long)
+                .is_some()
+        );
+        assert!(
+            PathLimits::windows_extended()
+                .check_relative(10, &long)
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_audit_portability_flags_each_platform() {
+        let paths = vec![PathBuf::from("short.txt"), deep_path(10, 30, "report.pdf")];
+
+        let violations = audit_portability(&paths);
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].platform, TargetPlatform::Windows);
+        assert_eq!(violations[0].entry_path, paths[1]);
+    }
+
+    #[test]
+    fn test_shorten_name_keeps_extension_and_tag() {
+        let name = format!("{}.pdf", "x".repeat(300));
+        let shortened = shorten_name(TargetPlatform::Linux, &name, 255, "deadbeef").unwrap();
+
+        assert_eq!(shortened.len(), 255);
+        assert!(shortened.ends_with("~deadbeef.pdf"));
+        assert!(shortened.starts_with("xxx"));
+    }
+
+    #[test]
+    fn test_shorten_name_respects_multibyte_boundaries() {
+        let name = "\u{e9}".repeat(200);
+        let shortened = shorten_name(TargetPlatform::MacOs, &name, 100, "deadbeef").unwrap();
+
+        assert!(TargetPlatform::MacOs.measure(&shortened) <= 100);
+        assert!(shortened.ends_with("~deadbeef"));
+    }
+
+    #[test]
+    fn test_shorten_name_without_room() {
+        assert!(shorten_name(TargetPlatform::Linux, "file.txt", 8, "deadbeef").is_none());
+    }
+
+    #[test]
+    fn test_shorten_parent_path_is_stable() {
+        let limits = PathLimits::for_platform(TargetPlatform::Linux);
+        let long_dir = "d".repeat(300);
+        let a = PathBuf::from(format!("{long_dir}/a.txt"));
+        let b = PathBuf::from(format!("{long_dir}/b.txt"));
+
+        let parent_a = shorten_parent_path(&limits, &a).unwrap();
+        let parent_b = shorten_parent_path(&limits, &b).unwrap();
+
+        assert_eq!(parent_a, parent_b);
+        assert_eq!(parent_a.to_string_lossy().len(), 255);
+    }
+
+    #[test]
+    fn test_file_name_budget() {
+        let limits = PathLimits::for_platform(TargetPlatform::Windows);
+        // 260 - (100 + (1 + 9) + 2) = 148
+        assert_eq!(file_name_budget(&limits, 100, Path::new("documents")), 148);
+        // Capped by the component limit
+        let linux = PathLimits::for_platform(TargetPlatform::Linux);
+        assert_eq!(file_name_budget(&linux, 10, Path::new("")), 255);
+    }
+}
@@ -0,0 +1,393 @@
+//! Archive safety limits
+//!
+//! Bounds on what one archive may hold, so a crafted or damaged payload
+//! can't exhaust disk space or inodes when it is restored. Archive creation
+//! refuses to go past them, and the vault manifest records the limits an
+//! archive was written under. Extraction trusts recorded limits that are at
+//! least as strict as its own; any other archive is pre-scanned, streaming,
+//! before a single file is written.
+
+use super::super::{FileOpsError, Result};
+use crate::constants::{
+    EXPANSION_RATIO_MIN_ENTRY_SIZE, MAX_ARCHIVE_ENTRIES, MAX_ARCHIVE_PATH_DEPTH,
+    MAX_ARCHIVE_UNCOMPRESSED_SIZE, MAX_ENTRY_EXPANSION_RATIO,
+};
+use flate2::read::GzDecoder;
+use serde::{Deserialize, Serialize};
+use std::cell::Cell;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, Read, Write};
+use std::path::{Component, Path};
+use std::rc::Rc;
+use tar::Archive;
+use tracing::{debug, warn};
+
+/// Compressed bytes the decoder may have read ahead of the entry it is on
+///
+/// Added to each entry's measured compressed size during a pre-scan, so
+/// buffering can only make an entry look less expanded than it is.
+const DECODER_READ_AHEAD_ALLOWANCE: u64 = 64 * 1024;
+
+/// Which safety limit an archive went past
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SafetyLimit {
+    EntryCount,
+    PathDepth,
+    ExpansionRatio,
+    TotalSize,
+}
+
+impl fmt::Display for SafetyLimit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            SafetyLimit::EntryCount => "entry count",
+            SafetyLimit::PathDepth => "path depth",
+            SafetyLimit::ExpansionRatio => "expansion ratio",
+            SafetyLimit::TotalSize => "total uncompressed size",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Limits an archive is written and restored under
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArchiveSafetyLimits {
+    /// Files and directories
+    pub max_entries: u64,
+    /// Path components in one entry
+    pub max_path_depth: u64,
+    /// Uncompressed-to-compressed ratio of one large entry
+    pub max_expansion_ratio: u64,
+    /// Uncompressed bytes across all entries
+    pub max_total_size: u64,
+}
+
+impl Default for ArchiveSafetyLimits {
+    fn default() -> Self {
+        Self {
+            max_entries: MAX_ARCHIVE_ENTRIES,
+            max_path_depth: MAX_ARCHIVE_PATH_DEPTH,
+            max_expansion_ratio: MAX_ENTRY_EXPANSION_RATIO,
+            max_total_size: MAX_ARCHIVE_UNCOMPRESSED_SIZE,
+        }
+    }
+}
+
+impl ArchiveSafetyLimits {
+    /// Whether an archive written under these limits is also within `required`
+    pub fn satisfies(&self, required: &ArchiveSafetyLimits) -> bool {
+        self.max_entries <= required.max_entries
+            && self.max_path_depth <= required.max_path_depth
+            && self.max_expansion_ratio <= required.max_expansion_ratio
+            && self.max_total_size <= required.max_total_size
+    }
+}
+
+/// Running totals, checked entry by entry
+#[derive(Debug)]
+pub struct SafetyTally {
+    limits: ArchiveSafetyLimits,
+    entries: u64,
+    total_size: u64,
+}
+
+impl SafetyTally {
+    pub fn new(limits: ArchiveSafetyLimits) -> Self {
+        Self {
+            limits,
+            entries: 0,
+            total_size: 0,
+        }
+    }
+
+    /// Count one entry of `size` uncompressed bytes stored at `path`
+    pub fn add_entry(&mut self, path: &Path, size: u64) -> Result<()> {
+        self.entries += 1;
+        check(
+            SafetyLimit::EntryCount,
+            self.entries,
+            self.limits.max_entries,
+        )?;
+
+        let depth = path
+            .components()
+            .filter(|component| matches!(component, Component::Normal(_)))
+            .count() as u64;
+        check(SafetyLimit::PathDepth, depth, self.limits.max_path_depth)?;
+
+        self.total_size = self.total_size.saturating_add(size);
+        check(
+            SafetyLimit::TotalSize,
+            self.total_size,
+            self.limits.max_total_size,
+        )
+    }
+
+    /// Check how far an entry of `size` bytes expanded from `compressed` bytes
+    ///
+    /// Entries under `EXPANSION_RATIO_MIN_ENTRY_SIZE` are too small to matter.
+    pub fn check_expansion(&self, size: u64, compressed: u64) -> Result<()> {
+        if size < EXPANSION_RATIO_MIN_ENTRY_SIZE {
+            return Ok(());
+        }
+        check(
+            SafetyLimit::ExpansionRatio,
+            size / compressed.max(1),
+            self.limits.max_expansion_ratio,
+        )
+    }
+}
+
+fn check(which: SafetyLimit, observed: u64, limit: u64) -> Result<()> {
+    if observed > limit {
+        warn!(%which, observed, limit, "Archive exceeds safety limits");
+        return Err(FileOpsError::ArchiveExceedsSafetyLimits {
+            which,
+            observed,
+            limit,
+        });
+    }
+    Ok(())
+}
+
+/// Reader or writer that counts the bytes passing through it
+pub(crate) struct ByteCounter<T> {
+    inner: T,
+    count: Rc<Cell<u64>>,
+}
+
+impl<T> ByteCounter<T> {
+    /// Wrap `inner`; the returned cell follows the running count
+    pub(crate) fn new(inner: T) -> (Self, Rc<Cell<u64>>) {
+        let count = Rc::new(Cell::new(0));
+        let counter = Self {
+            inner,
+            count: Rc::clone(&count),
+        };
+        (counter, count)
+    }
+
+    pub(crate) fn into_inner(self) -> T {
+        self.inner
+    }
+}
+
+impl<R: Read> Read for ByteCounter<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        self.count.set(self.count.get() + read as u64);
+        Ok(read)
+    }
+}
+
+impl<W: Write> Write for ByteCounter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        self.count.set(self.count.get() + written as u64);
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Check a TAR.GZ archive against `limits` without writing anything
+///
+/// Entries are counted and their declared sizes totalled before their data
+/// is read, so an oversized entry is refused without decompressing it.
+pub fn prescan_archive(archive_path: &Path, limits: ArchiveSafetyLimits) -> Result<()> {
+    let archive_file =
+        File::open(archive_path).map_err(|e| FileOpsError::ArchiveExtractionFailed {
+            message: format!("Failed to open archive: {e}"),
+        })?;
+    let (counted, compressed_read) = ByteCounter::new(archive_file);
+    let mut archive = Archive::new(GzDecoder::new(counted));
+    let mut tally = SafetyTally::new(limits);
+
+    for entry_result in archive
+        .entries()
+        .map_err(|e| FileOpsError::ArchiveExtractionFailed {
+            message: format!("Failed to read archive entries: {e}"),
+        })?
+    {
+        let mut entry = entry_result.map_err(|e| FileOpsError::ArchiveExtractionFailed {
+            message: format!("Failed to read archive entry: {e}"),
+        })?;
+        let path = entry
+            .path()
+            .map_err(|e| FileOpsError::ArchiveExtractionFailed {
+                message: format!("Failed to get entry path: {e}"),
+            })?
+            .into_owned();
+        let declared_size = entry.header().size().unwrap_or(0);
+        tally.add_entry(&path, declared_size)?;
+
+        let compressed_before = compressed_read.get();
+        let size = io::copy(&mut entry, &mut io::sink()).map_err(|e| {
+            FileOpsError::ArchiveExtractionFailed {
+                message: format!("Failed to read archive entry: {e}"),
+            }
+        })?;
+        let compressed = compressed_read.get() - compressed_before + DECODER_READ_AHEAD_ALLOWANCE;
+        tally.check_expansion(size, compressed)?;
+    }
+
+    debug!(
+        entries = tally.entries,
+        total_size = tally.total_size,
+        "Archive is within safety limits"
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::file::infrastructure::file_operations::archive_operations::creation::create_tar_gz;
+    use crate::services::file::infrastructure::file_operations::{FileOpsConfig, StagingArea};
+    use flate2::Compression;
+    use flate2::write::GzEncoder;
+    use tar::{Builder, Header};
+    use tempfile::TempDir;
+
+    const SMALL: ArchiveSafetyLimits = ArchiveSafetyLimits {
+        max_entries: 3,
+        max_path_depth: 2,
+        max_expansion_ratio: 10,
+        max_total_size: 100,
+    };
+
+    fn exceeded(result: Result<()>) -> (SafetyLimit, u64, u64) {
+        match result {
+            Err(FileOpsError::ArchiveExceedsSafetyLimits {
+                which,
+                observed,
+                limit,
+            }) => (which, observed, limit),
+            other => panic!("expected ArchiveExceedsSafetyLimits, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_each_limit_allows_its_boundary_and_refuses_past_it() {
+        let mut tally = SafetyTally::new(SMALL);
+        for name in ["a", "b", "c"] {
+            tally.add_entry(Path::new(name), 0).unwrap();
+        }
+        assert_eq!(
+            exceeded(tally.add_entry(Path::new("d"), 0)),
+            (SafetyLimit::EntryCount, 4, 3)
+        );
+
+        let mut tally = SafetyTally::new(SMALL);
+        tally.add_entry(Path::new("docs/a.txt"), 0).unwrap();
+        assert_eq!(
+            exceeded(tally.add_entry(Path::new("docs/deep/a.txt"), 0)),
+            (SafetyLimit::PathDepth, 3, 2)
+        );
+
+        let mut tally = SafetyTally::new(SMALL);
+        tally.add_entry(Path::new("a"), 100).unwrap();
+        assert_eq!(
+            exceeded(tally.add_entry(Path::new("b"), 1)),
+            (SafetyLimit::TotalSize, 101, 100)
+        );
+
+        let tally = SafetyTally::new(SMALL);
+        let size = EXPANSION_RATIO_MIN_ENTRY_SIZE;
+        tally.check_expansion(size, size / 10).unwrap();
+        assert_eq!(
+            exceeded(tally.check_expansion(size, size / 10 - 1)).0,
+            SafetyLimit::ExpansionRatio
+        );
+        tally.check_expansion(size - 1, 1).unwrap();
+    }
+
+    #[test]
+    fn test_creation_refuses_archives_past_the_limits() {
+        let temp = TempDir::new().unwrap();
+        let mut staging = StagingArea::new_in(temp.path()).unwrap();
+        staging.add_file_content("a.txt", &[b'a'; 60]).unwrap();
+        staging.add_file_content("b.txt", &[b'b'; 40]).unwrap();
+        let output = temp.path().join("out.tar.gz");
+
+        let at_limit = FileOpsConfig {
+            safety_limits: SMALL,
+            ..FileOpsConfig::default()
+        };
+        create_tar_gz(&staging, &output, &at_limit).unwrap();
+
+        let below = FileOpsConfig {
+            safety_limits: ArchiveSafetyLimits {
+                max_total_size: 99,
+                ..SMALL
+            },
+            ..FileOpsConfig::default()
+        };
+        std::fs::remove_file(&output).unwrap();
+        assert_eq!(
+            exceeded(create_tar_gz(&staging, &output, &below).map(|_| ())),
+            (SafetyLimit::TotalSize, 100, 99)
+        );
+        assert!(!output.exists());
+
+        // A run of zeros compresses far past 10:1
+        staging
+            .add_file_content(
+                "zeros.bin",
+                &vec![0; EXPANSION_RATIO_MIN_ENTRY_SIZE as usize],
+            )
+            .unwrap();
+        let ratio = FileOpsConfig {
+            safety_limits: ArchiveSafetyLimits {
+                max_total_size: u64::MAX,
+                ..SMALL
+            },
+            ..FileOpsConfig::default()
+        };
+        assert_eq!(
+            exceeded(create_tar_gz(&staging, &output, &ratio).map(|_| ())).0,
+            SafetyLimit::ExpansionRatio
+        );
+        assert!(!output.exists());
+    }
+
+    #[test]
+    fn test_prescan_catches_a_million_empty_entries() {
+        let temp = TempDir::new().unwrap();
+        let archive_path = temp.path().join("entries.tar.gz");
+        let file = File::create(&archive_path).unwrap();
+        let mut builder = Builder::new(GzEncoder::new(file, Compression::fast()));
+        for index in 0..1_000_000u32 {
+            let mut header = Header::new_gnu();
+            header.set_size(0);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder
+                .append_data(&mut header, format!("e/{index}"), io::empty())
+                .unwrap();
+        }
+        builder.into_inner().unwrap().finish().unwrap();
+
+        let limits = ArchiveSafetyLimits::default();
+        assert_eq!(
+            exceeded(prescan_archive(&archive_path, limits)),
+            (
+                SafetyLimit::EntryCount,
+                limits.max_entries + 1,
+                limits.max_entries
+            )
+        );
+    }
+
+    #[test]
+    fn test_recorded_limits_satisfy_only_equal_or_stricter_ones() {
+        let current = ArchiveSafetyLimits::default();
+        assert!(current.satisfies(&current));
+        assert!(SMALL.satisfies(&current));
+        assert!(!current.satisfies(&SMALL));
+    }
+}
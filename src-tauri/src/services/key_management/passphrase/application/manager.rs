@@ -1,15 +1,21 @@
 use super::services::{
-    GeneratedKey, GenerationError, GenerationService, ValidationError, ValidationService,
-    VaultIntegrationError, VaultIntegrationService,
+    CreatedRecoveryShares, GeneratedKey, GenerationError, GenerationService, KeyFileRepairError,
+    KeyFileRepairService, ReconstructedRecovery, RecoveryShareError, RecoveryShareService,
+    RepairedKeyFile, ValidationError, ValidationService, VaultIntegrationError,
+    VaultIntegrationService,
 };
-use crate::services::key_management::passphrase::domain::ValidationResult;
+use crate::services::key_management::passphrase::domain::{KeyFileDiagnosis, ValidationResult};
 use crate::services::key_management::shared::domain::models::VaultKey;
 use crate::services::vault::VaultMetadata;
+use std::path::Path;
+use zeroize::Zeroizing;
 
 pub struct PassphraseManager {
     generation_service: GenerationService,
     validation_service: ValidationService,
     vault_service: VaultIntegrationService,
+    recovery_share_service: RecoveryShareService,
+    key_file_service: KeyFileRepairService,
 }
 
 impl PassphraseManager {
@@ -18,6 +24,8 @@ pub fn new() -> Self {
             generation_service: GenerationService::new(),
             validation_service: ValidationService::new(),
             vault_service: VaultIntegrationService::new(),
+            recovery_share_service: RecoveryShareService::new(),
+            key_file_service: KeyFileRepairService::new(),
         }
     }
 
@@ -81,6 +89,41 @@ pub async fn validate_vault_has_passphrase_key(
             .validate_vault_has_passphrase_key(vault_id)
             .await
     }
+
+    pub async fn create_recovery_shares(
+        &self,
+        vault_id: &str,
+        threshold: u8,
+        total: u8,
+    ) -> Result<CreatedRecoveryShares, RecoveryShareError> {
+        self.recovery_share_service
+            .create_recovery_shares(vault_id, threshold, total)
+            .await
+    }
+
+    pub fn recovery_share(
+        &self,
+        vault_id: &str,
+        number: u8,
+    ) -> Result<Zeroizing<String>, RecoveryShareError> {
+        self.recovery_share_service.held_share(vault_id, number)
+    }
+
+    pub fn reconstruct_recovery_secret(
+        &self,
+        vault_id: &str,
+        shares: &[String],
+    ) -> Result<ReconstructedRecovery, RecoveryShareError> {
+        self.recovery_share_service.reconstruct(vault_id, shares)
+    }
+
+    pub fn validate_key_file(&self, path: &Path) -> Result<KeyFileDiagnosis, KeyFileRepairError> {
+        self.key_file_service.validate(path)
+    }
+
+    pub fn repair_key_file(&self, path: &Path) -> Result<RepairedKeyFile, KeyFileRepairError> {
+        self.key_file_service.repair(path)
+    }
 }
 
 impl Default for PassphraseManager {
@@ -3,6 +3,8 @@
 
 pub use manager::PassphraseManager;
 pub use services::{
-    GeneratedKey, GenerationError, GenerationService, ValidationError, ValidationService,
+    CreatedRecoveryShares, GeneratedKey, GenerationError, GenerationService, HeldRecoveryShares,
+    KeyFileRepairError, KeyFileRepairService, ReconstructedRecovery, RecoveryShareError,
+    RecoveryShareService, RepairedKeyFile, UnlockService, ValidationError, ValidationService,
     VaultIntegrationError, VaultIntegrationService,
 };
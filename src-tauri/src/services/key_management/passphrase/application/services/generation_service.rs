@@ -1,9 +1,8 @@
 use crate::services::key_management::passphrase::infrastructure::{
-    PassphraseKeyRepository, StorageError, encrypt_private_key, generate_keypair,
+    PassphraseKeyRepository, StorageError, generate_keypair, wrap_private_key,
 };
 use crate::services::shared::infrastructure::sanitize_label;
 use crate::services::vault::VaultMetadata;
-use age::secrecy::SecretString;
 use std::path::PathBuf;
 
 pub type Result<T> = std::result::Result<T, GenerationError>;
@@ -65,10 +64,7 @@ pub fn generate_passphrase_key(&self, label: &str, passphrase: &str) -> Result<G
             GenerationError::KeyGenerationFailed(format!("Failed to sanitize label: {}", e))
         })?;
 
-        let encrypted_key = encrypt_private_key(
-            &keypair.private_key,
-            SecretString::from(passphrase.to_string()),
-        )?;
+        let encrypted_key = wrap_private_key(&keypair.private_key, passphrase)?;
 
         let saved_path = PassphraseKeyRepository::save_encrypted_key(
             &sanitized.sanitized,
@@ -103,10 +99,7 @@ pub fn generate_with_metadata(
             GenerationError::KeyGenerationFailed(format!("Failed to sanitize label: {}", e))
         })?;
 
-        let encrypted_key = encrypt_private_key(
-            &keypair.private_key,
-            SecretString::from(passphrase.to_string()),
-        )?;
+        let encrypted_key = wrap_private_key(&keypair.private_key, passphrase)?;
 
         let saved_path = crate::services::key_management::shared::save_encrypted_key_with_metadata(
             &sanitized.sanitized,
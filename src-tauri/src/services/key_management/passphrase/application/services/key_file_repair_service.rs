@@ -0,0 +1,386 @@
+//! Key file validation and repair
+//!
+//! Checks a key file's structure without its passphrase and writes a fixed
+//! copy beside a damaged one. The original is never modified, so a repair
+//! that goes wrong loses nothing.
+
+use crate::services::key_management::passphrase::domain::{
+    KeyFileDiagnosis, diagnose_key_file, repair_key_file_bytes,
+};
+use std::fs::{self, OpenOptions};
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use tracing::{debug, info};
+
+/// Larger files aren't key files; matches the import limit
+const MAX_KEY_FILE_SIZE: u64 = 100_000;
+
+/// Name endings kept after the `-repaired` marker, longest first
+const KEY_FILE_SUFFIXES: [&str; 3] = [".agekey.enc", ".enc", ".age"];
+
+pub type Result<T> = std::result::Result<T, KeyFileRepairError>;
+
+#[derive(Debug)]
+pub enum KeyFileRepairError {
+    Io(std::io::Error),
+    TooLarge(u64),
+    /// The file has no issues to repair
+    NothingToRepair,
+    /// At least one issue can't be repaired
+    NotRepairable(KeyFileDiagnosis),
+}
+
+impl From<std::io::Error> for KeyFileRepairError {
+    fn from(err: std::io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+impl std::fmt::Display for KeyFileRepairError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "IO error: {}", err),
+            Self::TooLarge(size) => write!(
+                f,
+                "File too large ({} bytes) to be a key file; the limit is {} bytes",
+                size, MAX_KEY_FILE_SIZE
+            ),
+            Self::NothingToRepair => write!(f, "The key file has nothing to repair"),
+            Self::NotRepairable(diagnosis) => write!(f, "{}", diagnosis.summary()),
+        }
+    }
+}
+
+impl std::error::Error for KeyFileRepairError {}
+
+/// A fixed copy of a damaged key file
+#[derive(Debug, Clone)]
+pub struct RepairedKeyFile {
+    pub repaired_path: PathBuf,
+    /// Issues found in the original, all of them fixed in the copy
+    pub fixed: KeyFileDiagnosis,
+}
+
+pub struct KeyFileRepairService;
+
+impl KeyFileRepairService {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Check a key file's structure
+    pub fn validate(&self, path: &Path) -> Result<KeyFileDiagnosis> {
+        let diagnosis = diagnose_key_file(&read_key_file(path)?);
+        debug!(
+            path = %path.display(),
+            issues = diagnosis.issues.len(),
+            repairable = diagnosis.repairable,
+            "Validated key file"
+        );
+        Ok(diagnosis)
+    }
+
+    /// Write a repaired copy of a key file beside it
+    ///
+    /// The copy is named `<name>-repaired` before the key file extension,
+    /// with a counter if that name is taken, and is checked again once
+    /// written.
+    pub fn repair(&self, path: &Path) -> Result<RepairedKeyFile> {
+        let bytes = read_key_file(path)?;
+        let diagnosis = diagnose_key_file(&bytes);
+        if diagnosis.is_clean() {
+            return Err(KeyFileRepairError::NothingToRepair);
+        }
+        let Some(repaired) = repair_key_file_bytes(&bytes) else {
+            return Err(KeyFileRepairError::NotRepairable(diagnosis));
+        };
+
+        let repaired_path = write_beside(path, &repaired)?;
+        let recheck = diagnose_key_file(&fs::read(&repaired_path)?);
+        if !recheck.is_clean() {
+            // Never leave a copy that looks fixed but isn't
+            let _ = fs::remove_file(&repaired_path);
+            return Err(KeyFileRepairError::NotRepairable(recheck));
+        }
+
+        info!(
+            path = %path.display(),
+            repaired_path = %repaired_path.display(),
+            fixed = diagnosis.issues.len(),
+            "Wrote repaired key file"
+        );
+        Ok(RepairedKeyFile {
+            repaired_path,
+            fixed: diagnosis,
+        })
+    }
+}
+
+impl Default for KeyFileRepairService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn read_key_file(path: &Path) -> Result<Vec<u8>> {
+    let size = fs::metadata(path)?.len();
+    if size > MAX_KEY_FILE_SIZE {
+        return Err(KeyFileRepairError::TooLarge(size));
+    }
+    Ok(fs::read(path)?)
+}
+
+/// Create the first free `-repaired` name beside `original` with `bytes`
+fn write_beside(original: &Path, bytes: &[u8]) -> Result<PathBuf> {
+    let name = original
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let suffix = KEY_FILE_SUFFIXES
+        .iter()
+        .find(|suffix| name.len() > suffix.len() && name.ends_with(*suffix))
+        .copied()
+        .unwrap_or_default();
+    let stem = &name[..name.len() - suffix.len()];
+
+    for attempt in 1.. {
+        let marker = match attempt {
+            1 => "-repaired".to_string(),
+            n => format!("-repaired-{n}"),
+        };
+        let candidate = original.with_file_name(format!("{stem}{marker}{suffix}"));
+        match OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&candidate)
+        {
+            Ok(mut file) => {
+                file.write_all(bytes)?;
+                file.sync_all()?;
+                return Ok(candidate);
+            }
+            Err(e) if e.kind() == ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(e.into()),
+        }
+    }
+    unreachable!("ran out of repaired file names")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::crypto::infrastructure::CryptoError;
+    use crate::services::key_management::passphrase::domain::KeyFileIssueKind;
+    use crate::services::key_management::passphrase::infrastructure::{
+        decrypt_private_key, encrypt_private_key, generate_keypair,
+    };
+    use age::secrecy::SecretString;
+    use base64::Engine as _;
+    use base64::engine::general_purpose::STANDARD;
+    use tempfile::TempDir;
+
+    const PASSPHRASE: &str = "Harbor-Lantern-42!";
+
+    /// A real key file whose payload has no CRLF, so a CRLF conversion can
+    /// be undone exactly
+    fn key_file() -> (String, Vec<u8>) {
+        loop {
+            let keypair = generate_keypair().unwrap();
+            let file = encrypt_private_key(
+                &keypair.private_key,
+                SecretString::from(PASSPHRASE.to_string()),
+            )
+            .unwrap();
+            if !file.windows(2).any(|pair| pair == b"\r\n") {
+                let secret = keypair.private_key.expose_secret().to_string();
+                return (secret, file);
+            }
+        }
+    }
+
+    fn header_len(file: &[u8]) -> usize {
+        let mac = file.windows(4).position(|w| w == b"\n---").unwrap() + 1;
+        mac + file[mac..].iter().position(|b| *b == b'\n').unwrap() + 1
+    }
+
+    fn to_crlf(bytes: &[u8]) -> Vec<u8> {
+        let mut out = Vec::new();
+        for &b in bytes {
+            if b == b'\n' {
+                out.push(b'\r');
+            }
+            out.push(b);
+        }
+        out
+    }
+
+    fn armored(file: &[u8], columns: usize) -> Vec<u8> {
+        let encoded = STANDARD.encode(file);
+        let mut text = "-----BEGIN AGE ENCRYPTED FILE-----\n".to_string();
+        for chunk in encoded.as_bytes().chunks(columns) {
+            text.push_str(std::str::from_utf8(chunk).unwrap());
+            text.push('\n');
+        }
+        text.push_str("-----END AGE ENCRYPTED FILE-----\n");
+        text.into_bytes()
+    }
+
+    fn kinds(diagnosis: &KeyFileDiagnosis) -> Vec<KeyFileIssueKind> {
+        diagnosis.issues.iter().map(|issue| issue.kind).collect()
+    }
+
+    #[test]
+    fn test_repairable_damage_is_fixed_in_a_copy_that_unlocks() {
+        let temp_dir = TempDir::new().unwrap();
+        let service = KeyFileRepairService::new();
+        let (secret, file) = key_file();
+        let header = header_len(&file);
+        let header_text = String::from_utf8(file[..header].to_vec()).unwrap();
+
+        // The scrypt stanza line wraps its 43-character body onto two lines
+        let rewrapped = {
+            let body_start = header_text.find("\n-> ").unwrap() + 1;
+            let body_start = body_start + header_text[body_start..].find('\n').unwrap() + 1;
+            let mut text = header_text.clone();
+            text.insert(body_start + 20, '\n');
+            let mut damaged = text.into_bytes();
+            damaged.extend_from_slice(&file[header..]);
+            damaged
+        };
+        let trailing_spaces = {
+            let mut damaged = header_text.replacen('\n', "  \n", 2).into_bytes();
+            damaged.extend_from_slice(&file[header..]);
+            damaged
+        };
+        let with_bom = [b"\xEF\xBB\xBF".as_slice(), &file].concat();
+
+        let cases = [
+            ("bom", with_bom, vec![KeyFileIssueKind::ByteOrderMark]),
+            (
+                "crlf",
+                to_crlf(&file),
+                vec![KeyFileIssueKind::WindowsLineEndings],
+            ),
+            (
+                "spaces",
+                trailing_spaces,
+                vec![KeyFileIssueKind::TrailingWhitespace],
+            ),
+            ("wrap", rewrapped, vec![KeyFileIssueKind::BrokenLineWrap]),
+            (
+                "armor",
+                armored(&file, 64),
+                vec![KeyFileIssueKind::AsciiArmor],
+            ),
+            (
+                "armor-rewrapped",
+                to_crlf(&armored(&file, 76)),
+                vec![
+                    KeyFileIssueKind::AsciiArmor,
+                    KeyFileIssueKind::WindowsLineEndings,
+                    KeyFileIssueKind::BrokenLineWrap,
+                ],
+            ),
+        ];
+        for (name, damaged, expected) in cases {
+            let path = temp_dir.path().join(format!("{name}.agekey.enc"));
+            fs::write(&path, &damaged).unwrap();
+
+            let diagnosis = service.validate(&path).unwrap();
+            assert_eq!(kinds(&diagnosis), expected, "{name}");
+            assert!(diagnosis.repairable, "{name}");
+            assert!(matches!(
+                decrypt_private_key(&damaged, SecretString::from(PASSPHRASE.to_string())),
+                Err(CryptoError::InvalidKeyFormat(_))
+            ));
+
+            let repaired = service.repair(&path).unwrap();
+            assert_eq!(
+                repaired.repaired_path,
+                temp_dir.path().join(format!("{name}-repaired.agekey.enc"))
+            );
+            assert_eq!(fs::read(&path).unwrap(), damaged, "{name} left as it was");
+            let fixed = fs::read(&repaired.repaired_path).unwrap();
+            assert_eq!(fixed, file, "{name}");
+            let unlocked =
+                decrypt_private_key(&fixed, SecretString::from(PASSPHRASE.to_string())).unwrap();
+            assert_eq!(unlocked.expose_secret(), secret);
+        }
+    }
+
+    #[test]
+    fn test_fatal_damage_is_reported_and_nothing_written() {
+        let temp_dir = TempDir::new().unwrap();
+        let service = KeyFileRepairService::new();
+        let (_, file) = key_file();
+        let header = header_len(&file);
+
+        let mut bad_mac = file.clone();
+        bad_mac[header - 5] = b'!';
+        let cases = [
+            (
+                "cut-header",
+                file[..header / 2].to_vec(),
+                KeyFileIssueKind::Truncated,
+            ),
+            (
+                "cut-payload",
+                file[..file.len() - 10].to_vec(),
+                KeyFileIssueKind::Truncated,
+            ),
+            (
+                "cut-armor",
+                armored(&file, 64)[..120].to_vec(),
+                KeyFileIssueKind::Truncated,
+            ),
+            ("bad-mac", bad_mac, KeyFileIssueKind::CorruptHeader),
+            (
+                "plain-text",
+                b"AGE-SECRET-KEY-1ABC\n".to_vec(),
+                KeyFileIssueKind::NotAnAgeFile,
+            ),
+        ];
+        for (name, damaged, expected) in cases {
+            let path = temp_dir.path().join(format!("{name}.agekey.enc"));
+            fs::write(&path, &damaged).unwrap();
+
+            let diagnosis = service.validate(&path).unwrap();
+            assert!(diagnosis.has(expected), "{name}: {diagnosis:?}");
+            assert!(!diagnosis.repairable, "{name}");
+            assert!(matches!(
+                service.repair(&path),
+                Err(KeyFileRepairError::NotRepairable(_))
+            ));
+        }
+
+        let intact = temp_dir.path().join("intact.agekey.enc");
+        fs::write(&intact, &file).unwrap();
+        assert!(service.validate(&intact).unwrap().is_clean());
+        assert!(matches!(
+            service.repair(&intact),
+            Err(KeyFileRepairError::NothingToRepair)
+        ));
+
+        let entries = fs::read_dir(temp_dir.path()).unwrap().count();
+        assert_eq!(entries, 6, "no repaired copies written");
+    }
+
+    #[test]
+    fn test_repair_never_replaces_an_existing_copy() {
+        let temp_dir = TempDir::new().unwrap();
+        let (_, file) = key_file();
+        let path = temp_dir.path().join("backup.enc");
+        fs::write(&path, to_crlf(&file)).unwrap();
+        fs::write(temp_dir.path().join("backup-repaired.enc"), b"keep").unwrap();
+
+        let repaired = KeyFileRepairService::new().repair(&path).unwrap();
+        assert_eq!(
+            repaired.repaired_path,
+            temp_dir.path().join("backup-repaired-2.enc")
+        );
+        assert_eq!(
+            fs::read(temp_dir.path().join("backup-repaired.enc")).unwrap(),
+            b"keep"
+        );
+    }
+}
@@ -1,7 +1,16 @@
 mod generation_service;
+mod key_file_repair_service;
+mod recovery_share_service;
+mod unlock_service;
 mod validation_service;
 mod vault_integration_service;
 
 pub use generation_service::{GeneratedKey, GenerationError, GenerationService};
+pub use key_file_repair_service::{KeyFileRepairError, KeyFileRepairService, RepairedKeyFile};
+pub use recovery_share_service::{
+    CreatedRecoveryShares, HeldRecoveryShares, ReconstructedRecovery, RecoveryShareError,
+    RecoveryShareService,
+};
+pub use unlock_service::UnlockService;
 pub use validation_service::{ValidationError, ValidationService};
 pub use vault_integration_service::{VaultIntegrationError, VaultIntegrationService};
@@ -0,0 +1,308 @@
+use crate::services::key_management::passphrase::domain::PassphraseError;
+use crate::services::key_management::passphrase::domain::recovery_shares::{
+    RecoveryShare, combine_shares, generate_recovery_secret, recovery_secret_hash, split_secret,
+    validate_share_config,
+};
+use crate::services::key_management::passphrase::infrastructure::{
+    PassphraseKeyRepository, StorageError, encrypt_private_key, generate_keypair,
+};
+use crate::services::key_management::shared::infrastructure::{KeyRegistry, RecoveryShareConfig};
+use crate::services::shared::infrastructure::sanitize_label;
+use crate::services::vault;
+use age::secrecy::SecretString;
+use chrono::Utc;
+use std::collections::HashMap;
+use std::sync::{Mutex, MutexGuard, OnceLock};
+use std::time::{Duration, Instant};
+use zeroize::{Zeroize, Zeroizing};
+
+pub type Result<T> = std::result::Result<T, RecoveryShareError>;
+
+/// Length of the random recovery secret in bytes
+const RECOVERY_SECRET_LEN: usize = 32;
+
+/// Time a vault's new shares stay in memory so each can be copied to its
+/// holder; after that they are gone for good
+pub const RECOVERY_SHARE_HOLD: Duration = Duration::from_secs(600);
+
+#[derive(Debug)]
+pub enum RecoveryShareError {
+    Storage(StorageError),
+    Share(PassphraseError),
+    VaultNotFound(String),
+    AlreadyConfigured(String),
+    NotConfigured(String),
+    KeyGenerationFailed(String),
+    VerificationFailed,
+    ShareNotHeld(String),
+}
+
+impl From<StorageError> for RecoveryShareError {
+    fn from(err: StorageError) -> Self {
+        Self::Storage(err)
+    }
+}
+
+impl From<PassphraseError> for RecoveryShareError {
+    fn from(err: PassphraseError) -> Self {
+        Self::Share(err)
+    }
+}
+
+impl From<crate::services::crypto::infrastructure::CryptoError> for RecoveryShareError {
+    fn from(err: crate::services::crypto::infrastructure::CryptoError) -> Self {
+        Self::KeyGenerationFailed(err.to_string())
+    }
+}
+
+impl std::fmt::Display for RecoveryShareError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Storage(err) => write!(f, "Storage error: {}", err),
+            Self::Share(err) => write!(f, "{}", err),
+            Self::VaultNotFound(id) => write!(f, "Vault '{}' not found", id),
+            Self::AlreadyConfigured(id) => {
+                write!(f, "Vault '{}' already has recovery shares", id)
+            }
+            Self::NotConfigured(id) => {
+                write!(f, "Vault '{}' has no recovery shares configured", id)
+            }
+            Self::KeyGenerationFailed(msg) => write!(f, "Key generation failed: {}", msg),
+            Self::VerificationFailed => write!(
+                f,
+                "Reconstructed secret does not match - shares may be from a different vault"
+            ),
+            Self::ShareNotHeld(id) => write!(
+                f,
+                "Recovery shares of vault '{}' are no longer held - they can only be copied right after creation",
+                id
+            ),
+        }
+    }
+}
+
+impl std::error::Error for RecoveryShareError {}
+
+/// Shares produced for a vault, shown to the user exactly once
+pub struct CreatedRecoveryShares {
+    pub shares: Vec<String>,
+    pub threshold: u8,
+    pub total: u8,
+    pub public_key: String,
+}
+
+/// Secret reconstructed from shares, ready to unlock the recovery identity
+pub struct ReconstructedRecovery {
+    pub passphrase: SecretString,
+    pub key_filename: String,
+}
+
+pub struct RecoveryShareService;
+
+impl RecoveryShareService {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Create a recovery identity for the vault and split its unlock secret
+    ///
+    /// The identity's public key is recorded in the registry and added as a
+    /// recipient on the next encryption of the vault.
+    pub async fn create_recovery_shares(
+        &self,
+        vault_id: &str,
+        threshold: u8,
+        total: u8,
+    ) -> Result<CreatedRecoveryShares> {
+        validate_share_config(threshold, total)?;
+
+        let metadata = vault::get_vault(vault_id)
+            .await
+            .map_err(|_| RecoveryShareError::VaultNotFound(vault_id.to_string()))?;
+
+        let mut registry =
+            KeyRegistry::load().map_err(|e| StorageError::RegistryLoadFailed(e.to_string()))?;
+
+        if registry.recovery_shares.contains_key(vault_id) {
+            return Err(RecoveryShareError::AlreadyConfigured(vault_id.to_string()));
+        }
+
+        let sanitized = sanitize_label(&format!(
+            "{}-recovery-shares",
+            metadata.vault.sanitized_name
+        ))
+        .map_err(|e| RecoveryShareError::KeyGenerationFailed(e.to_string()))?;
+
+        let mut secret = generate_recovery_secret(RECOVERY_SECRET_LEN);
+        let verification_hash = recovery_secret_hash(&secret);
+        let split = split_secret(&secret, threshold, total);
+
+        let keypair = generate_keypair()?;
+        let encrypted_key =
+            encrypt_private_key(&keypair.private_key, secret_to_passphrase(&secret))?;
+        secret.zeroize();
+        let shares = split?;
+
+        let public_key = keypair.public_key.to_string();
+        PassphraseKeyRepository::save_encrypted_key(
+            &sanitized.sanitized,
+            &encrypted_key,
+            Some(&public_key),
+        )?;
+
+        registry.recovery_shares.insert(
+            vault_id.to_string(),
+            RecoveryShareConfig {
+                threshold,
+                total,
+                verification_hash,
+                public_key: public_key.clone(),
+                key_filename: format!("{}.agekey.enc", sanitized.sanitized),
+                created_at: Utc::now(),
+            },
+        );
+        registry
+            .save()
+            .map_err(|e| StorageError::RegistrySaveFailed(e.to_string()))?;
+
+        let shares: Vec<String> = shares.iter().map(RecoveryShare::encode).collect();
+        HeldRecoveryShares::global().hold(vault_id, &shares, Instant::now());
+
+        Ok(CreatedRecoveryShares {
+            shares,
+            threshold,
+            total,
+            public_key,
+        })
+    }
+
+    /// Validate share checksums and rebuild the recovery identity passphrase
+    pub fn reconstruct(&self, vault_id: &str, shares: &[String]) -> Result<ReconstructedRecovery> {
+        let registry =
+            KeyRegistry::load().map_err(|e| StorageError::RegistryLoadFailed(e.to_string()))?;
+
+        let config = registry
+            .recovery_shares
+            .get(vault_id)
+            .ok_or_else(|| RecoveryShareError::NotConfigured(vault_id.to_string()))?;
+
+        let parsed = shares
+            .iter()
+            .map(|s| RecoveryShare::parse(s))
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+
+        if parsed.iter().any(|s| s.threshold != config.threshold) {
+            return Err(PassphraseError::InvalidRecoveryShare(
+                "Share does not belong to this vault's recovery set".to_string(),
+            )
+            .into());
+        }
+
+        let mut secret = combine_shares(&parsed)?;
+        let matches = recovery_secret_hash(&secret) == config.verification_hash;
+        let passphrase = secret_to_passphrase(&secret);
+        secret.zeroize();
+
+        if !matches {
+            return Err(RecoveryShareError::VerificationFailed);
+        }
+
+        Ok(ReconstructedRecovery {
+            passphrase,
+            key_filename: config.key_filename.clone(),
+        })
+    }
+
+    /// Share `number` (1-based) of the vault's set, while it is still held
+    /// after creation
+    pub fn held_share(&self, vault_id: &str, number: u8) -> Result<Zeroizing<String>> {
+        HeldRecoveryShares::global().share(vault_id, number, Instant::now())
+    }
+}
+
+/// New shares of a vault, held briefly for copying
+struct HeldShares {
+    shares: Vec<Zeroizing<String>>,
+    held_until: Instant,
+}
+
+/// Recovery shares held in memory after creation, by vault
+///
+/// Dropping a vault's shares zeroizes them, whether they expire, are
+/// replaced or are forgotten by a panic lock.
+#[derive(Default)]
+pub struct HeldRecoveryShares {
+    vaults: Mutex<HashMap<String, HeldShares>>,
+}
+
+impl HeldRecoveryShares {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Shares held by the running app
+    pub fn global() -> &'static Self {
+        static HELD: OnceLock<HeldRecoveryShares> = OnceLock::new();
+        HELD.get_or_init(Self::new)
+    }
+
+    /// Lock the store, dropping sets whose time is up at `now`
+    fn lock(&self, now: Instant) -> MutexGuard<'_, HashMap<String, HeldShares>> {
+        let mut vaults = self.vaults.lock().unwrap_or_else(|e| e.into_inner());
+        vaults.retain(|_, shares| shares.held_until > now);
+        vaults
+    }
+
+    /// Hold a vault's new shares for `RECOVERY_SHARE_HOLD` from `now`
+    pub fn hold(&self, vault_id: &str, shares: &[String], now: Instant) {
+        self.lock(now).insert(
+            vault_id.to_string(),
+            HeldShares {
+                shares: shares.iter().cloned().map(Zeroizing::new).collect(),
+                held_until: now + RECOVERY_SHARE_HOLD,
+            },
+        );
+    }
+
+    /// Share `number` (1-based) of the vault's set, if still held at `now`
+    pub fn share(&self, vault_id: &str, number: u8, now: Instant) -> Result<Zeroizing<String>> {
+        let held = self.lock(now);
+        let shares = held
+            .get(vault_id)
+            .ok_or_else(|| RecoveryShareError::ShareNotHeld(vault_id.to_string()))?;
+        shares
+            .shares
+            .get(usize::from(number).wrapping_sub(1))
+            .cloned()
+            .ok_or_else(|| {
+                PassphraseError::InvalidInput(format!(
+                    "Share number must be between 1 and {}",
+                    shares.shares.len()
+                ))
+                .into()
+            })
+    }
+
+    /// Number of vaults whose shares are still held at `now`
+    pub fn held_vaults(&self, now: Instant) -> usize {
+        self.lock(now).len()
+    }
+
+    /// Zeroize every held share, returning how many vaults' sets were held
+    pub fn forget_all(&self, now: Instant) -> usize {
+        let mut held = self.lock(now);
+        let forgotten = held.len();
+        held.clear();
+        forgotten
+    }
+}
+
+impl Default for RecoveryShareService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn secret_to_passphrase(secret: &[u8]) -> SecretString {
+    SecretString::from(hex::encode(secret))
+}
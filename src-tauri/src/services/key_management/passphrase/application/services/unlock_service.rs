@@ -0,0 +1,59 @@
+//! Passphrase Unlock Service
+//!
+//! Unlocks registered passphrase keys with what the user typed, under the
+//! normalization policy the key was protected with. Keys from before
+//! normalization are re-protected under the current policy once they
+//! unlock; a failed re-wrap is logged and tried again on the next unlock.
+
+use crate::prelude::*;
+use crate::services::crypto::infrastructure::{PrivateKey, Result};
+use crate::services::key_management::passphrase::infrastructure::{
+    PassphraseKeyRepository, unwrap_private_key, wrap_private_key,
+};
+
+pub struct UnlockService;
+
+impl UnlockService {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Unlock a loaded key file, re-wrapping it when its policy is outdated
+    pub fn unlock(
+        &self,
+        key_id: &str,
+        encrypted_key: &[u8],
+        passphrase: &str,
+        policy: u32,
+    ) -> Result<PrivateKey> {
+        let unlocked = unwrap_private_key(encrypted_key, passphrase, policy)?;
+        if unlocked.needs_rewrap {
+            self.rewrap(key_id, &unlocked.private_key, passphrase);
+        }
+        Ok(unlocked.private_key)
+    }
+
+    fn rewrap(&self, key_id: &str, private_key: &PrivateKey, passphrase: &str) {
+        let result = wrap_private_key(private_key, passphrase)
+            .map_err(|e| e.to_string())
+            .and_then(|encrypted| {
+                PassphraseKeyRepository::rewrap_key_file(key_id, &encrypted)
+                    .map_err(|e| e.to_string())
+            });
+        match result {
+            Ok(()) => info!(
+                key_id,
+                "Re-protected key under the current passphrase policy"
+            ),
+            Err(e) => {
+                warn!(key_id, error = %e, "Couldn't re-protect key; will retry on next unlock")
+            }
+        }
+    }
+}
+
+impl Default for UnlockService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -1,10 +1,10 @@
+use super::UnlockService;
 use crate::services::key_management::passphrase::domain::{
-    ValidationResult, calculate_strength_score,
+    ValidationResult, calculate_strength_score, normalize_passphrase,
 };
 use crate::services::key_management::passphrase::infrastructure::{
-    PassphraseKeyRepository, StorageError, decrypt_private_key,
+    PassphraseKeyRepository, StorageError,
 };
-use age::secrecy::SecretString;
 
 pub type Result<T> = std::result::Result<T, ValidationError>;
 
@@ -38,8 +38,9 @@ pub fn new() -> Self {
         Self
     }
 
+    /// Score the passphrase as it will protect the key, after normalization
     pub fn validate_strength(&self, passphrase: &str) -> ValidationResult {
-        calculate_strength_score(passphrase)
+        calculate_strength_score(&normalize_passphrase(passphrase))
     }
 
     pub fn verify_key_passphrase(&self, key_id: &str, passphrase: &str) -> Result<bool> {
@@ -47,15 +48,21 @@ pub fn verify_key_passphrase(&self, key_id: &str, passphrase: &str) -> Result<bo
 
         match key_entry {
             crate::services::key_management::shared::KeyEntry::Passphrase {
-                key_filename, ..
+                key_filename,
+                key_location,
+                passphrase_policy,
+                ..
             } => {
-                let encrypted_key = PassphraseKeyRepository::load_encrypted_key(&key_filename)?;
+                let encrypted_key =
+                    PassphraseKeyRepository::load_key_file(&key_filename, key_location.as_ref())?;
 
-                let passphrase_secret = SecretString::from(passphrase.to_string());
-                match decrypt_private_key(&encrypted_key, passphrase_secret) {
-                    Ok(_) => Ok(true),
-                    Err(_) => Ok(false),
-                }
+                let unlocked = UnlockService::new().unlock(
+                    key_id,
+                    &encrypted_key,
+                    passphrase,
+                    passphrase_policy,
+                );
+                Ok(unlocked.is_ok())
             }
             _ => Err(ValidationError::InvalidPassphrase),
         }
@@ -87,4 +94,13 @@ fn test_validate_strength_strong() {
         assert!(result.is_valid);
         assert!(result.score > 70);
     }
+
+    #[test]
+    fn test_validate_strength_ignores_surrounding_spaces() {
+        let service = ValidationService::new();
+        let padded = service.validate_strength("  \u{00A0}MySecure#Pass2024!\u{200B} ");
+        let plain = service.validate_strength("MySecure#Pass2024!");
+        assert_eq!(padded.score, plain.score);
+        assert_eq!(padded.is_valid, plain.is_valid);
+    }
 }
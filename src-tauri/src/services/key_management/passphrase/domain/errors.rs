@@ -10,6 +10,8 @@ pub enum PassphraseError {
     StorageFailed(String),
     KeyNotFound(String),
     InvalidInput(String),
+    InvalidRecoveryShare(String),
+    InsufficientRecoveryShares { provided: usize, required: u8 },
 }
 
 impl fmt::Display for PassphraseError {
@@ -30,6 +32,12 @@ fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
             Self::StorageFailed(msg) => write!(f, "Storage operation failed: {}", msg),
             Self::KeyNotFound(key_id) => write!(f, "Key '{}' not found", key_id),
             Self::InvalidInput(msg) => write!(f, "Invalid input: {}", msg),
+            Self::InvalidRecoveryShare(msg) => write!(f, "Invalid recovery share: {}", msg),
+            Self::InsufficientRecoveryShares { provided, required } => write!(
+                f,
+                "Not enough recovery shares: {} provided, {} required",
+                provided, required
+            ),
         }
     }
 }
@@ -40,7 +48,11 @@ impl PassphraseError {
     pub fn is_recoverable(&self) -> bool {
         matches!(
             self,
-            Self::WeakPassphrase { .. } | Self::WrongPassphrase | Self::InvalidInput(_)
+            Self::WeakPassphrase { .. }
+                | Self::WrongPassphrase
+                | Self::InvalidInput(_)
+                | Self::InvalidRecoveryShare(_)
+                | Self::InsufficientRecoveryShares { .. }
         )
     }
 
@@ -53,6 +65,12 @@ pub fn recovery_guidance(&self) -> Option<&'static str> {
             Self::InvalidInput(_) => Some("Please provide valid input"),
             Self::KeyNotFound(_) => Some("Verify the key exists in the registry"),
             Self::StorageFailed(_) => Some("Check file permissions and disk space"),
+            Self::InvalidRecoveryShare(_) => {
+                Some("Re-enter the share exactly as it was recorded, including dashes")
+            }
+            Self::InsufficientRecoveryShares { .. } => {
+                Some("Collect shares from more holders until the threshold is met")
+            }
             _ => None,
         }
     }
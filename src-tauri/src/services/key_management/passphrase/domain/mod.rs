@@ -1,5 +1,12 @@
 pub mod errors;
 pub mod models;
+pub mod recovery_shares;
 
 pub use errors::PassphraseError;
-pub use models::{PassphraseStrength, ValidationResult, calculate_strength_score};
+pub use models::{
+    KeyFileDiagnosis, KeyFileIssue, KeyFileIssueKind, LEGACY_PASSPHRASE_POLICY,
+    PASSPHRASE_POLICY_VERSION, PassphraseStrength, ValidationResult, calculate_strength_score,
+    diagnose_key_file, needs_rewrap, normalize_passphrase, passphrase_candidates,
+    repair_key_file_bytes,
+};
+pub use recovery_shares::{RecoveryShare, combine_shares, split_secret, validate_share_config};
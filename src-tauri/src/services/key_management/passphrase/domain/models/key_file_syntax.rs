@@ -0,0 +1,559 @@
+//! Key file syntax checks
+//!
+//! A passphrase key file is an age file: a text header of stanzas wrapped at
+//! 64 columns, closed by a `---` MAC line, then the encrypted identity.
+//! Moving one through email or a text editor can add a byte order mark,
+//! turn line endings into CRLF, leave trailing spaces, rewrap the base64
+//! lines, ASCII-armor it or cut it short, and age then rejects it with a
+//! bare parse error. These checks look at the file's structure only, without
+//! the passphrase, and say what's wrong and whether it can be undone.
+//!
+//! Age writes its header in exactly one form, and the MAC covers those
+//! bytes, so putting a damaged header back in that form restores the file.
+
+use base64::Engine as _;
+use base64::engine::general_purpose::STANDARD;
+use serde::Serialize;
+
+/// First line of every age v1 file
+const AGE_VERSION_LINE: &str = "age-encryption.org/v1";
+
+const ARMOR_BEGIN: &str = "-----BEGIN AGE ENCRYPTED FILE-----";
+const ARMOR_END: &str = "-----END AGE ENCRYPTED FILE-----";
+
+const BYTE_ORDER_MARK: &[u8] = b"\xEF\xBB\xBF";
+
+/// Width of base64 lines in the header and in armor
+const LINE_COLUMNS: usize = 64;
+
+/// Base64 length of the header MAC and of a wrapped file key (32 bytes)
+const ENCODED_32_BYTES: usize = 43;
+
+/// Payload nonce plus the tag of its final chunk
+const MIN_PAYLOAD_LEN: usize = 16 + 16;
+
+/// Payload of a passphrase-wrapped x25519 identity (74 characters)
+const IDENTITY_PAYLOAD_LEN: usize = MIN_PAYLOAD_LEN + 74;
+
+/// What's wrong with a key file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, specta::Type)]
+#[serde(rename_all = "snake_case")]
+pub enum KeyFileIssueKind {
+    ByteOrderMark,
+    WindowsLineEndings,
+    TrailingWhitespace,
+    /// Base64 lines rewrapped to another width or split
+    BrokenLineWrap,
+    AsciiArmor,
+    Truncated,
+    NotAnAgeFile,
+    /// Header text that isn't age syntax and can't be put back
+    CorruptHeader,
+}
+
+impl KeyFileIssueKind {
+    /// Whether `repair_key_file` can undo it
+    pub fn is_repairable(self) -> bool {
+        matches!(
+            self,
+            Self::ByteOrderMark
+                | Self::WindowsLineEndings
+                | Self::TrailingWhitespace
+                | Self::BrokenLineWrap
+                | Self::AsciiArmor
+        )
+    }
+}
+
+/// One problem found in a key file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, specta::Type)]
+pub struct KeyFileIssue {
+    pub kind: KeyFileIssueKind,
+    pub repairable: bool,
+    /// First line it was seen on, counting from 1
+    pub line: Option<usize>,
+    pub message: String,
+}
+
+/// Everything found wrong with a key file; empty when it looks intact
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, specta::Type)]
+pub struct KeyFileDiagnosis {
+    /// At most one issue of each kind
+    pub issues: Vec<KeyFileIssue>,
+    /// There are issues and repairing fixes all of them
+    pub repairable: bool,
+}
+
+impl KeyFileDiagnosis {
+    pub fn is_clean(&self) -> bool {
+        self.issues.is_empty()
+    }
+
+    pub fn has(&self, kind: KeyFileIssueKind) -> bool {
+        self.issues.iter().any(|issue| issue.kind == kind)
+    }
+
+    /// One sentence for error messages, naming the fix when there is one
+    pub fn summary(&self) -> String {
+        if self.is_clean() {
+            return "The key file's structure looks intact".to_string();
+        }
+        if let Some(fatal) = self.issues.iter().find(|issue| !issue.repairable) {
+            return format!("The key file can't be repaired: {}", fatal.message);
+        }
+        let found: Vec<&str> = self
+            .issues
+            .iter()
+            .map(|issue| issue.message.as_str())
+            .collect();
+        format!(
+            "The key file {}. Repair it to write a fixed copy beside it",
+            found.join("; ")
+        )
+    }
+}
+
+/// Check the structure of a key file's bytes
+pub fn diagnose_key_file(bytes: &[u8]) -> KeyFileDiagnosis {
+    inspect(bytes).0
+}
+
+/// The key file's bytes with every issue fixed
+///
+/// `None` when the file has no issues or one that can't be repaired.
+pub fn repair_key_file_bytes(bytes: &[u8]) -> Option<Vec<u8>> {
+    match inspect(bytes) {
+        (diagnosis, Some(repaired)) if diagnosis.repairable => Some(repaired),
+        _ => None,
+    }
+}
+
+/// Issues seen so far, one per kind
+#[derive(Default)]
+struct Findings {
+    issues: Vec<KeyFileIssue>,
+}
+
+impl Findings {
+    fn add(&mut self, kind: KeyFileIssueKind, line: Option<usize>, message: impl Into<String>) {
+        if self.issues.iter().all(|issue| issue.kind != kind) {
+            self.issues.push(KeyFileIssue {
+                kind,
+                repairable: kind.is_repairable(),
+                line,
+                message: message.into(),
+            });
+        }
+    }
+
+    fn into_diagnosis(self) -> KeyFileDiagnosis {
+        let repairable =
+            !self.issues.is_empty() && self.issues.iter().all(|issue| issue.repairable);
+        KeyFileDiagnosis {
+            issues: self.issues,
+            repairable,
+        }
+    }
+}
+
+/// Diagnosis and, unless something is fatal, the file in age's own form
+fn inspect(bytes: &[u8]) -> (KeyFileDiagnosis, Option<Vec<u8>>) {
+    let mut findings = Findings::default();
+    let mut data = bytes;
+    if let Some(rest) = data.strip_prefix(BYTE_ORDER_MARK) {
+        findings.add(
+            KeyFileIssueKind::ByteOrderMark,
+            Some(1),
+            "starts with a byte order mark, as some text editors add",
+        );
+        data = rest;
+    }
+    if data.is_empty() {
+        findings.add(KeyFileIssueKind::Truncated, None, "is empty");
+        return (findings.into_diagnosis(), None);
+    }
+
+    let dearmored;
+    if data.trim_ascii_start().starts_with(ARMOR_BEGIN.as_bytes()) {
+        findings.add(
+            KeyFileIssueKind::AsciiArmor,
+            None,
+            "is ASCII-armored; the app keeps key files in binary form",
+        );
+        match dearmor(data, &mut findings) {
+            Some(binary) => {
+                dearmored = binary;
+                data = &dearmored;
+            }
+            None => return (findings.into_diagnosis(), None),
+        }
+    }
+
+    let canonical = canonical_binary(data, &mut findings);
+    (findings.into_diagnosis(), canonical)
+}
+
+/// A line with its CR and trailing spaces removed, noting either
+fn clean_line<'a>(line: &'a [u8], number: usize, findings: &mut Findings) -> &'a [u8] {
+    let mut line = line;
+    if let Some(rest) = line.strip_suffix(b"\r") {
+        findings.add(
+            KeyFileIssueKind::WindowsLineEndings,
+            Some(number),
+            "has Windows line endings (CRLF), as email or Notepad can leave",
+        );
+        line = rest;
+    }
+    let trimmed = line.trim_ascii_end();
+    if trimmed.len() != line.len() {
+        findings.add(
+            KeyFileIssueKind::TrailingWhitespace,
+            Some(number),
+            format!("has spaces at the end of line {number}"),
+        );
+    }
+    trimmed
+}
+
+fn is_base64(text: &[u8], padded: bool) -> bool {
+    text.iter()
+        .all(|b| b.is_ascii_alphanumeric() || *b == b'+' || *b == b'/' || (padded && *b == b'='))
+}
+
+/// Whether base64 lines are wrapped as age writes them: full lines, then a
+/// shorter last line
+fn wrapped_canonically(lines: &[&[u8]], last_may_be_full: bool) -> bool {
+    // Even an empty body has a line
+    let Some((last, full)) = lines.split_last() else {
+        return false;
+    };
+    full.iter().all(|line| line.len() == LINE_COLUMNS)
+        && (last.len() < LINE_COLUMNS || (last_may_be_full && last.len() == LINE_COLUMNS))
+}
+
+/// Decode ASCII armor to the binary file inside it
+fn dearmor(data: &[u8], findings: &mut Findings) -> Option<Vec<u8>> {
+    let mut encoded: Vec<&[u8]> = Vec::new();
+    let mut begun = false;
+    let mut ended = false;
+    let mut first_body_line = None;
+    for (index, line) in data.split(|b| *b == b'\n').enumerate() {
+        let number = index + 1;
+        let line = clean_line(line, number, findings);
+        if !begun {
+            begun = line == ARMOR_BEGIN.as_bytes();
+            continue;
+        }
+        if line == ARMOR_END.as_bytes() {
+            ended = true;
+            break;
+        }
+        if !is_base64(line, true) {
+            findings.add(
+                KeyFileIssueKind::CorruptHeader,
+                Some(number),
+                format!("line {number} of the armor isn't base64"),
+            );
+            return None;
+        }
+        first_body_line.get_or_insert(number);
+        encoded.push(line);
+    }
+    if !ended {
+        findings.add(
+            KeyFileIssueKind::Truncated,
+            None,
+            "ends before the armor's END line; part of it is missing",
+        );
+        return None;
+    }
+
+    // Armor lines are all full but the last, which may be full too
+    let encoded: Vec<&[u8]> = encoded.into_iter().filter(|l| !l.is_empty()).collect();
+    if encoded.is_empty() {
+        findings.add(KeyFileIssueKind::Truncated, None, "has empty armor");
+        return None;
+    }
+    if !wrapped_canonically(&encoded, true) {
+        findings.add(
+            KeyFileIssueKind::BrokenLineWrap,
+            first_body_line,
+            "has armor lines wrapped at another width",
+        );
+    }
+    let joined = encoded.concat();
+    if joined.len() % 4 != 0 {
+        findings.add(
+            KeyFileIssueKind::Truncated,
+            None,
+            "is missing part of its armor",
+        );
+        return None;
+    }
+    match STANDARD.decode(&joined) {
+        Ok(binary) => Some(binary),
+        Err(_) => {
+            findings.add(
+                KeyFileIssueKind::CorruptHeader,
+                first_body_line,
+                "has armor that isn't valid base64",
+            );
+            None
+        }
+    }
+}
+
+/// Lines of the header, split on LF
+struct HeaderLines<'a> {
+    data: &'a [u8],
+    /// Start of the next line
+    offset: usize,
+    number: usize,
+    /// Some line so far ended in CRLF
+    crlf: bool,
+}
+
+impl<'a> HeaderLines<'a> {
+    /// The next whole line, or `None` at the end of the data
+    fn next_line(&mut self) -> Option<(usize, &'a [u8])> {
+        let rest = &self.data[self.offset..];
+        let end = rest.iter().position(|b| *b == b'\n')?;
+        self.offset += end + 1;
+        self.number += 1;
+        self.crlf |= rest[..end].ends_with(b"\r");
+        Some((self.number, &rest[..end]))
+    }
+}
+
+/// The binary file rebuilt with a canonical header, or `None` if fatal
+fn canonical_binary(data: &[u8], findings: &mut Findings) -> Option<Vec<u8>> {
+    let truncated = |findings: &mut Findings| {
+        findings.add(
+            KeyFileIssueKind::Truncated,
+            None,
+            "ends in the middle of its header; part of it is missing",
+        );
+        None
+    };
+    let mut lines = HeaderLines {
+        data,
+        offset: 0,
+        number: 0,
+        crlf: false,
+    };
+
+    let Some((number, version)) = lines.next_line() else {
+        if data.starts_with(AGE_VERSION_LINE.as_bytes()) {
+            return truncated(findings);
+        }
+        findings.add(
+            KeyFileIssueKind::NotAnAgeFile,
+            Some(1),
+            "isn't an age-encrypted key file",
+        );
+        return None;
+    };
+    let version = clean_line(version, number, findings);
+    if version != AGE_VERSION_LINE.as_bytes() {
+        let message = if version.starts_with(b"age-encryption.org/") {
+            "was written by an age version this app can't read"
+        } else {
+            "isn't an age-encrypted key file"
+        };
+        findings.add(KeyFileIssueKind::NotAnAgeFile, Some(1), message);
+        return None;
+    }
+
+    let mut header = format!("{AGE_VERSION_LINE}\n").into_bytes();
+    let mut stanza_types: Vec<String> = Vec::new();
+    let mut pending = lines.next_line();
+    let mac = loop {
+        let Some((number, line)) = pending else {
+            return truncated(findings);
+        };
+        let line = clean_line(line, number, findings);
+
+        if let Some(mac) = line.strip_prefix(b"--- ") {
+            if mac.len() != ENCODED_32_BYTES || !is_base64(mac, false) {
+                findings.add(
+                    KeyFileIssueKind::CorruptHeader,
+                    Some(number),
+                    format!("has a damaged MAC on line {number}"),
+                );
+                return None;
+            }
+            break mac;
+        }
+
+        let Some(arguments) = line.strip_prefix(b"-> ") else {
+            findings.add(
+                KeyFileIssueKind::CorruptHeader,
+                Some(number),
+                format!("has a damaged header on line {number}"),
+            );
+            return None;
+        };
+        let arguments = String::from_utf8_lossy(arguments).into_owned();
+        let stanza_type = arguments.split(' ').next().unwrap_or_default().to_string();
+
+        let mut body: Vec<&[u8]> = Vec::new();
+        let body_start = number + 1;
+        pending = loop {
+            let Some((number, line)) = lines.next_line() else {
+                return truncated(findings);
+            };
+            let cleaned = clean_line(line, number, findings);
+            if cleaned.starts_with(b"-") {
+                break Some((number, line));
+            }
+            if !is_base64(cleaned, false) {
+                findings.add(
+                    KeyFileIssueKind::CorruptHeader,
+                    Some(number),
+                    format!("has a damaged header on line {number}"),
+                );
+                return None;
+            }
+            body.push(cleaned);
+        };
+
+        if !wrapped_canonically(&body, false) {
+            findings.add(
+                KeyFileIssueKind::BrokenLineWrap,
+                Some(body_start),
+                format!("has header lines wrapped differently from line {body_start}"),
+            );
+        }
+        let body = body.concat();
+        if matches!(stanza_type.as_str(), "scrypt" | "X25519") && body.len() != ENCODED_32_BYTES {
+            let (kind, message) = if body.len() < ENCODED_32_BYTES {
+                (
+                    KeyFileIssueKind::Truncated,
+                    format!("is missing part of its wrapped key from line {body_start}"),
+                )
+            } else {
+                (
+                    KeyFileIssueKind::CorruptHeader,
+                    format!("has extra text in its wrapped key from line {body_start}"),
+                )
+            };
+            findings.add(kind, Some(body_start), message);
+            return None;
+        }
+
+        header.extend_from_slice(b"-> ");
+        header.extend_from_slice(arguments.as_bytes());
+        header.push(b'\n');
+        for chunk in body.chunks(LINE_COLUMNS) {
+            header.extend_from_slice(chunk);
+            header.push(b'\n');
+        }
+        if body.len() % LINE_COLUMNS == 0 {
+            header.push(b'\n');
+        }
+        stanza_types.push(stanza_type);
+    };
+    if stanza_types.is_empty() {
+        findings.add(
+            KeyFileIssueKind::CorruptHeader,
+            None,
+            "has no recipients in its header",
+        );
+        return None;
+    }
+    header.extend_from_slice(b"--- ");
+    header.extend_from_slice(mac);
+    header.push(b'\n');
+
+    // A file whose header lines gained CRs had every LF in the payload
+    // turned into CRLF as well
+    let payload = &data[lines.offset..];
+    let payload = if lines.crlf {
+        undo_crlf(payload)
+    } else {
+        payload.to_vec()
+    };
+    let smallest = if stanza_types.iter().any(|t| t == "scrypt") {
+        IDENTITY_PAYLOAD_LEN
+    } else {
+        MIN_PAYLOAD_LEN
+    };
+    if payload.len() < smallest {
+        findings.add(
+            KeyFileIssueKind::Truncated,
+            None,
+            "ends before the encrypted key does; part of it is missing",
+        );
+        return None;
+    }
+
+    header.extend_from_slice(&payload);
+    Some(header)
+}
+
+fn undo_crlf(bytes: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut iter = bytes.iter().peekable();
+    while let Some(&b) = iter.next() {
+        if b == b'\r' && iter.peek() == Some(&&b'\n') {
+            continue;
+        }
+        out.push(b);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Shape of an age passphrase key file; the bytes are made up
+    fn fixture() -> Vec<u8> {
+        let mut file = format!(
+            "{AGE_VERSION_LINE}\n-> scrypt c2FsdHNhbHRzYWx0c2FsdA 18\n{}\n--- {}\n",
+            "A".repeat(ENCODED_32_BYTES),
+            "B".repeat(ENCODED_32_BYTES)
+        )
+        .into_bytes();
+        file.extend((0..IDENTITY_PAYLOAD_LEN).map(|i| (i * 7 % 256) as u8));
+        file
+    }
+
+    fn kinds(diagnosis: &KeyFileDiagnosis) -> Vec<KeyFileIssueKind> {
+        diagnosis.issues.iter().map(|issue| issue.kind).collect()
+    }
+
+    #[test]
+    fn test_intact_file_has_no_issues() {
+        let diagnosis = diagnose_key_file(&fixture());
+        assert!(diagnosis.is_clean(), "{diagnosis:?}");
+        assert!(!diagnosis.repairable);
+        assert_eq!(repair_key_file_bytes(&fixture()), None);
+    }
+
+    #[test]
+    fn test_stanza_body_of_a_full_line_keeps_its_empty_line() {
+        let body = "C".repeat(LINE_COLUMNS);
+        let mut file = format!(
+            "{AGE_VERSION_LINE}\n-> other-type arg\n{body}\n\n--- {}\n",
+            "B".repeat(ENCODED_32_BYTES)
+        )
+        .into_bytes();
+        file.extend([0u8; MIN_PAYLOAD_LEN]);
+        assert!(diagnose_key_file(&file).is_clean());
+
+        // Without the empty line the body would run on; age rejects it
+        let damaged = String::from_utf8_lossy(&file).replacen("\n\n", "\n", 1);
+        let diagnosis = diagnose_key_file(damaged.as_bytes());
+        assert_eq!(kinds(&diagnosis), vec![KeyFileIssueKind::BrokenLineWrap]);
+        assert_eq!(repair_key_file_bytes(damaged.as_bytes()).unwrap(), file);
+    }
+
+    #[test]
+    fn test_not_an_age_file_is_fatal() {
+        let diagnosis = diagnose_key_file(b"AGE-SECRET-KEY-1QQQ\n");
+        assert_eq!(kinds(&diagnosis), vec![KeyFileIssueKind::NotAnAgeFile]);
+        assert!(!diagnosis.repairable);
+        assert!(diagnosis.summary().contains("can't be repaired"));
+    }
+}
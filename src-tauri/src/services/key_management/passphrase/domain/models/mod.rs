@@ -1,9 +1,18 @@
 //! Passphrase domain models
 
+pub mod key_file_syntax;
 pub mod passphrase_key_info;
+pub mod passphrase_normalization;
 pub mod passphrase_strength;
 pub mod validation_rules;
 
+pub use key_file_syntax::{
+    KeyFileDiagnosis, KeyFileIssue, KeyFileIssueKind, diagnose_key_file, repair_key_file_bytes,
+};
 pub use passphrase_key_info::PassphraseKeyInfo;
+pub use passphrase_normalization::{
+    LEGACY_PASSPHRASE_POLICY, PASSPHRASE_POLICY_VERSION, needs_rewrap, normalize_passphrase,
+    passphrase_candidates,
+};
 pub use passphrase_strength::PassphraseStrength;
 pub use validation_rules::{ValidationResult, calculate_strength_score};
@@ -0,0 +1,106 @@
+//! Passphrase normalization policy
+//!
+//! The same passphrase can reach the app as different bytes: macOS input
+//! methods compose accents differently from Linux ones, password managers
+//! paste non-breaking spaces, and a stray trailing space is invisible. Every
+//! passphrase is normalized the same way before it protects a key and
+//! before every unlock, so only what the user meant to type matters.
+//!
+//! The policy is versioned and recorded with each key. Keys from before the
+//! policy (version 0) were protected with the raw input, so unlocking them
+//! tries the raw input first and then the normalized form.
+
+use unicode_normalization::UnicodeNormalization;
+use zeroize::Zeroizing;
+
+/// Policy of keys protected with the passphrase exactly as typed
+pub const LEGACY_PASSPHRASE_POLICY: u32 = 0;
+
+/// Policy applied to newly protected keys
+///
+/// Version 1: NFC, space-like characters mapped to a regular space, and
+/// leading and trailing whitespace stripped.
+pub const PASSPHRASE_POLICY_VERSION: u32 = 1;
+
+/// Passphrase as the current policy protects and checks it
+pub fn normalize_passphrase(input: &str) -> Zeroizing<String> {
+    let mapped: Zeroizing<String> = Zeroizing::new(
+        input
+            .nfc()
+            .map(|c| if is_space_like(c) { ' ' } else { c })
+            .collect(),
+    );
+    Zeroizing::new(mapped.trim().to_string())
+}
+
+/// Forms of `input` to try, in order, against a key protected under `policy`
+pub fn passphrase_candidates(input: &str, policy: u32) -> Vec<Zeroizing<String>> {
+    let normalized = normalize_passphrase(input);
+    if policy >= PASSPHRASE_POLICY_VERSION || *normalized == input {
+        return vec![normalized];
+    }
+    vec![Zeroizing::new(input.to_string()), normalized]
+}
+
+/// Whether a key under `policy` should be re-protected with the current one
+pub fn needs_rewrap(policy: u32) -> bool {
+    policy < PASSPHRASE_POLICY_VERSION
+}
+
+/// Characters that look like a space, or like nothing, when typed or pasted
+fn is_space_like(c: char) -> bool {
+    matches!(
+        c,
+        '\u{00A0}' // no-break space
+            | '\u{2007}' // figure space
+            | '\u{202F}' // narrow no-break space
+            | '\u{3000}' // ideographic space
+            | '\u{200B}' // zero-width space
+            | '\u{2060}' // word joiner
+            | '\u{FEFF}' // zero-width no-break space (BOM)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_nfd_and_nfc_normalize_alike() {
+        let nfc = "Caf\u{e9} cr\u{e8}me 2024!";
+        let nfd = "Cafe\u{301} cre\u{300}me 2024!";
+        assert_ne!(nfc, nfd);
+        assert_eq!(*normalize_passphrase(nfd), nfc);
+        assert_eq!(normalize_passphrase(nfc), normalize_passphrase(nfd));
+    }
+
+    #[test]
+    fn test_pasted_spaces_are_regular_and_trimmed() {
+        assert_eq!(
+            *normalize_passphrase("\u{FEFF}correct\u{00A0}horse\u{200B}battery \t\n"),
+            "correct horse battery"
+        );
+        assert_eq!(
+            *normalize_passphrase("two\u{202F}\u{3000}spaces"),
+            "two  spaces"
+        );
+    }
+
+    #[test]
+    fn test_candidates_follow_policy() {
+        let current = passphrase_candidates("secret ", PASSPHRASE_POLICY_VERSION);
+        assert_eq!(current.len(), 1);
+        assert_eq!(*current[0], "secret");
+
+        let legacy = passphrase_candidates("secret ", LEGACY_PASSPHRASE_POLICY);
+        let legacy: Vec<&str> = legacy.iter().map(|c| c.as_str()).collect();
+        assert_eq!(legacy, vec!["secret ", "secret"]);
+
+        assert_eq!(
+            passphrase_candidates("secret", LEGACY_PASSPHRASE_POLICY).len(),
+            1
+        );
+        assert!(needs_rewrap(LEGACY_PASSPHRASE_POLICY));
+        assert!(!needs_rewrap(PASSPHRASE_POLICY_VERSION));
+    }
+}
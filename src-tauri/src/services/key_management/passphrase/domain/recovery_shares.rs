@@ -0,0 +1,445 @@
+//! Shamir secret sharing over GF(256)
+//!
+//! Splits a recovery secret into `total` shares so that any `threshold` of
+//! them reconstruct it, while fewer reveal nothing. Each byte of the secret is
+//! the constant term of an independent random polynomial of degree
+//! `threshold - 1`; share `i` holds the polynomial values at `x = i`.
+//!
+//! Arithmetic uses the AES field (reduction polynomial `x^8 + x^4 + x^3 + x + 1`)
+//! with log/exp tables.
+//!
+//! Share strings look like `BQS1-03-3-<base58 data>-<checksum>`: format
+//! version, share index, threshold, share bytes, and the first 4 bytes of a
+//! SHA-256 over the preceding fields (hex). The checksum catches typos and
+//! truncation before reconstruction is attempted.
+
+use super::errors::PassphraseError;
+use rand::RngCore;
+use sha2::{Digest, Sha256};
+use zeroize::Zeroize;
+
+/// Share string prefix (format version 1)
+const SHARE_PREFIX: &str = "BQS1";
+
+/// Number of hex characters in the share checksum
+const CHECKSUM_HEX_LEN: usize = 8;
+
+/// A single Shamir share
+#[derive(Clone, PartialEq, Eq)]
+pub struct RecoveryShare {
+    /// x-coordinate (1-255, never 0)
+    pub index: u8,
+    /// Number of shares required to reconstruct
+    pub threshold: u8,
+    /// Polynomial values for each secret byte
+    pub data: Vec<u8>,
+}
+
+/// Shows only the index and threshold; the share bytes never reach logs
+impl std::fmt::Debug for RecoveryShare {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RecoveryShare")
+            .field("index", &self.index)
+            .field("threshold", &self.threshold)
+            .field(
+                "data",
+                &format_args!("[redacted, {} bytes]", self.data.len()),
+            )
+            .finish()
+    }
+}
+
+impl Drop for RecoveryShare {
+    fn drop(&mut self) {
+        self.data.zeroize();
+    }
+}
+
+impl RecoveryShare {
+    /// Encode as a share string with checksum
+    pub fn encode(&self) -> String {
+        let body = format!(
+            "{}-{:02}-{}-{}",
+            SHARE_PREFIX,
+            self.index,
+            self.threshold,
+            bs58::encode(&self.data).into_string()
+        );
+        let checksum = share_checksum(&body);
+        format!("{body}-{checksum}")
+    }
+
+    /// Parse and checksum-verify a share string
+    pub fn parse(share: &str) -> Result<Self, PassphraseError> {
+        let share = share.trim();
+        let parts: Vec<&str> = share.split('-').collect();
+
+        let [prefix, index, threshold, data, checksum] = parts.as_slice() else {
+            return Err(PassphraseError::InvalidRecoveryShare(
+                "Share is not in the expected format".to_string(),
+            ));
+        };
+
+        if *prefix != SHARE_PREFIX {
+            return Err(PassphraseError::InvalidRecoveryShare(format!(
+                "Unsupported share format '{prefix}'"
+            )));
+        }
+
+        let body = format!("{prefix}-{index}-{threshold}-{data}");
+        if !checksum.eq_ignore_ascii_case(&share_checksum(&body)) {
+            return Err(PassphraseError::InvalidRecoveryShare(format!(
+                "Checksum mismatch for share {index} - check it was entered correctly"
+            )));
+        }
+
+        let index: u8 = index
+            .parse()
+            .map_err(|_| PassphraseError::InvalidRecoveryShare("Invalid share index".into()))?;
+        let threshold: u8 = threshold
+            .parse()
+            .map_err(|_| PassphraseError::InvalidRecoveryShare("Invalid threshold".into()))?;
+        let data = bs58::decode(data)
+            .into_vec()
+            .map_err(|_| PassphraseError::InvalidRecoveryShare("Invalid share data".into()))?;
+
+        if index == 0 || data.is_empty() {
+            return Err(PassphraseError::InvalidRecoveryShare(
+                "Share is malformed".to_string(),
+            ));
+        }
+
+        // A threshold below 2 would reconstruct from too few shares, or none
+        if threshold < 2 {
+            return Err(PassphraseError::InvalidRecoveryShare(format!(
+                "Share {index} has an invalid threshold of {threshold}"
+            )));
+        }
+
+        Ok(Self {
+            index,
+            threshold,
+            data,
+        })
+    }
+}
+
+/// Reject configurations that defeat the purpose of splitting
+pub fn validate_share_config(threshold: u8, total: u8) -> Result<(), PassphraseError> {
+    if threshold < 2 {
+        return Err(PassphraseError::InvalidInput(
+            "Threshold must be at least 2 - use a passphrase key for single-holder access"
+                .to_string(),
+        ));
+    }
+
+    if threshold > total {
+        return Err(PassphraseError::InvalidInput(format!(
+            "Threshold ({threshold}) cannot exceed the number of shares ({total})"
+        )));
+    }
+
+    Ok(())
+}
+
+/// Split a secret into `total` shares, any `threshold` of which reconstruct it
+pub fn split_secret(
+    secret: &[u8],
+    threshold: u8,
+    total: u8,
+) -> Result<Vec<RecoveryShare>, PassphraseError> {
+    validate_share_config(threshold, total)?;
+
+    if secret.is_empty() {
+        return Err(PassphraseError::InvalidInput(
+            "Secret cannot be empty".to_string(),
+        ));
+    }
+
+    let mut shares: Vec<RecoveryShare> = (1..=total)
+        .map(|index| RecoveryShare {
+            index,
+            threshold,
+            data: Vec::with_capacity(secret.len()),
+        })
+        .collect();
+
+    let mut rng = rand::thread_rng();
+    let mut coefficients = vec![0u8; threshold as usize];
+
+    for &byte in secret {
+        // coefficients[0] is the secret byte, the rest are random
+        coefficients[0] = byte;
+        rng.fill_bytes(&mut coefficients[1..]);
+
+        for share in &mut shares {
+            share.data.push(evaluate(&coefficients, share.index));
+        }
+    }
+
+    coefficients.zeroize();
+    Ok(shares)
+}
+
+/// Reconstruct a secret from at least `threshold` shares
+pub fn combine_shares(shares: &[RecoveryShare]) -> Result<Vec<u8>, PassphraseError> {
+    let first = shares
+        .first()
+        .ok_or(PassphraseError::InsufficientRecoveryShares {
+            provided: 0,
+            required: 0,
+        })?;
+
+    let threshold = first.threshold;
+    let secret_len = first.data.len();
+
+    if shares
+        .iter()
+        .any(|s| s.threshold != threshold || s.data.len() != secret_len)
+    {
+        return Err(PassphraseError::InvalidRecoveryShare(
+            "Shares belong to different recovery sets".to_string(),
+        ));
+    }
+
+    let mut indices: Vec<u8> = shares.iter().map(|s| s.index).collect();
+    indices.sort_unstable();
+    indices.dedup();
+    if indices.len() != shares.len() {
+        return Err(PassphraseError::InvalidRecoveryShare(
+            "The same share was entered more than once".to_string(),
+        ));
+    }
+
+    if shares.len() < threshold as usize {
+        return Err(PassphraseError::InsufficientRecoveryShares {
+            provided: shares.len(),
+            required: threshold,
+        });
+    }
+
+    let used = &shares[..threshold as usize];
+    let mut secret = Vec::with_capacity(secret_len);
+
+    for byte_idx in 0..secret_len {
+        // Lagrange interpolation at x = 0
+        let mut value = 0u8;
+        for (i, share_i) in used.iter().enumerate() {
+            let mut basis = 1u8;
+            for (j, share_j) in used.iter().enumerate() {
+                if i != j {
+                    // x_j / (x_j - x_i); subtraction is XOR in GF(256)
+                    basis = gf_mul(basis, gf_div(share_j.index, share_j.index ^ share_i.index));
+                }
+            }
+            value ^= gf_mul(share_i.data[byte_idx], basis);
+        }
+        secret.push(value);
+    }
+
+    Ok(secret)
+}
+
+/// Generate a random recovery secret
+pub fn generate_recovery_secret(len: usize) -> Vec<u8> {
+    let mut secret = vec![0u8; len];
+    rand::thread_rng().fill_bytes(&mut secret);
+    secret
+}
+
+/// Verification hash stored in the registry (never the secret or shares)
+pub fn recovery_secret_hash(secret: &[u8]) -> String {
+    hex::encode(Sha256::digest(secret))
+}
+
+fn share_checksum(body: &str) -> String {
+    hex::encode(Sha256::digest(body.as_bytes()))[..CHECKSUM_HEX_LEN].to_string()
+}
+
+/// Evaluate a polynomial at `x` using Horner's method
+fn evaluate(coefficients: &[u8], x: u8) -> u8 {
+    coefficients
+        .iter()
+        .rev()
+        .fold(0u8, |acc, &c| gf_mul(acc, x) ^ c)
+}
+
+/// Log/exp tables for GF(256) with generator 3
+struct GfTables {
+    exp: [u8; 512],
+    log: [u8; 256],
+}
+
+const GF_TABLES: GfTables = build_tables();
+
+const fn build_tables() -> GfTables {
+    let mut exp = [0u8; 512];
+    let mut log = [0u8; 256];
+    let mut x: u8 = 1;
+    let mut i = 0;
+
+    while i < 255 {
+        exp[i] = x;
+        log[x as usize] = i as u8;
+
+        // x *= 3  (x * 2 with reduction, then xor x)
+        let doubled = (x << 1) ^ if x & 0x80 != 0 { 0x1b } else { 0 };
+        x = doubled ^ x;
+        i += 1;
+    }
+
+    // Duplicate so exp[log a + log b] never needs a modulo
+    while i < 512 {
+        exp[i] = exp[i - 255];
+        i += 1;
+    }
+
+    GfTables { exp, log }
+}
+
+fn gf_mul(a: u8, b: u8) -> u8 {
+    if a == 0 || b == 0 {
+        return 0;
+    }
+    let sum = GF_TABLES.log[a as usize] as usize + GF_TABLES.log[b as usize] as usize;
+    GF_TABLES.exp[sum]
+}
+
+fn gf_div(a: u8, b: u8) -> u8 {
+    debug_assert!(b != 0, "Division by zero in GF(256)");
+    if a == 0 {
+        return 0;
+    }
+    let diff = 255 + GF_TABLES.log[a as usize] as usize - GF_TABLES.log[b as usize] as usize;
+    GF_TABLES.exp[diff]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gf_arithmetic() {
+        // Known AES field product: 0x57 * 0x83 = 0xc1
+        assert_eq!(gf_mul(0x57, 0x83), 0xc1);
+        for a in 1..=255u8 {
+            assert_eq!(gf_div(gf_mul(a, 0x53), 0x53), a);
+        }
+    }
+
+    #[test]
+    fn test_reconstruct_with_exactly_threshold_shares() {
+        let secret = generate_recovery_secret(32);
+        let shares = split_secret(&secret, 3, 5).unwrap();
+        assert_eq!(shares.len(), 5);
+
+        // Every 3-of-5 combination reconstructs the secret
+        for a in 0..5 {
+            for b in (a + 1)..5 {
+                for c in (b + 1)..5 {
+                    let subset = vec![shares[a].clone(), shares[b].clone(), shares[c].clone()];
+                    assert_eq!(combine_shares(&subset).unwrap(), secret);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_insufficient_shares() {
+        let secret = generate_recovery_secret(32);
+        let shares = split_secret(&secret, 3, 5).unwrap();
+
+        let result = combine_shares(&shares[..2]);
+        assert_eq!(
+            result,
+            Err(PassphraseError::InsufficientRecoveryShares {
+                provided: 2,
+                required: 3
+            })
+        );
+    }
+
+    #[test]
+    fn test_corrupted_share_fails_checksum() {
+        let shares = split_secret(&generate_recovery_secret(32), 3, 5).unwrap();
+        let encoded = shares[0].encode();
+
+        // Change one character of the share data
+        let data_start = encoded.rfind('-').unwrap() - 5;
+        let mut chars: Vec<char> = encoded.chars().collect();
+        chars[data_start] = if chars[data_start] == 'a' { 'b' } else { 'a' };
+        let corrupted: String = chars.into_iter().collect();
+
+        assert!(matches!(
+            RecoveryShare::parse(&corrupted),
+            Err(PassphraseError::InvalidRecoveryShare(_))
+        ));
+    }
+
+    #[test]
+    fn test_share_string_round_trip() {
+        let shares = split_secret(&generate_recovery_secret(32), 2, 3).unwrap();
+        for share in &shares {
+            let encoded = share.encode();
+            assert!(encoded.starts_with("BQS1-0"));
+            assert_eq!(&RecoveryShare::parse(&encoded).unwrap(), share);
+        }
+    }
+
+    #[test]
+    fn test_parse_rejects_degenerate_threshold_and_index() {
+        for (index, threshold) in [(1, 0), (1, 1), (0, 2)] {
+            let share = RecoveryShare {
+                index,
+                threshold,
+                data: vec![1, 2, 3],
+            };
+            assert!(matches!(
+                RecoveryShare::parse(&share.encode()),
+                Err(PassphraseError::InvalidRecoveryShare(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_debug_redacts_share_data() {
+        let share = RecoveryShare {
+            index: 2,
+            threshold: 3,
+            data: vec![0xab; 4],
+        };
+        let debug = format!("{share:?}");
+        assert!(debug.contains("index: 2"));
+        assert!(debug.contains("[redacted, 4 bytes]"));
+        assert!(!debug.contains("171"));
+    }
+
+    #[test]
+    fn test_duplicate_shares_rejected() {
+        let shares = split_secret(&generate_recovery_secret(16), 2, 3).unwrap();
+        let duplicated = vec![shares[0].clone(), shares[0].clone()];
+        assert!(matches!(
+            combine_shares(&duplicated),
+            Err(PassphraseError::InvalidRecoveryShare(_))
+        ));
+    }
+
+    #[test]
+    fn test_degenerate_configs_rejected() {
+        assert!(validate_share_config(1, 5).is_err());
+        assert!(validate_share_config(0, 5).is_err());
+        assert!(validate_share_config(4, 3).is_err());
+        assert!(validate_share_config(2, 2).is_ok());
+        assert!(validate_share_config(3, 5).is_ok());
+    }
+
+    #[test]
+    fn test_recovery_secret_hash_is_stable() {
+        let secret = [7u8; 32];
+        assert_eq!(recovery_secret_hash(&secret), recovery_secret_hash(&secret));
+        assert_ne!(
+            recovery_secret_hash(&secret),
+            recovery_secret_hash(&[8u8; 32])
+        );
+    }
+}
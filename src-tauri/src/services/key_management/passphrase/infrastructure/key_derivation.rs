@@ -7,6 +7,17 @@
 use crate::services::crypto::infrastructure::{
     CryptoError, KeyPair, PrivateKey, PublicKey, Result,
 };
+use crate::services::key_management::passphrase::domain::{
+    diagnose_key_file, needs_rewrap, normalize_passphrase, passphrase_candidates,
+};
+
+/// A private key unlocked with a typed passphrase
+pub struct UnlockedPrivateKey {
+    pub private_key: PrivateKey,
+    /// The key file predates the current normalization policy and should be
+    /// re-protected with [`wrap_private_key`]
+    pub needs_rewrap: bool,
+}
 
 pub fn generate_keypair() -> Result<KeyPair> {
     let identity = Identity::generate();
@@ -62,7 +73,7 @@ pub fn decrypt_private_key(encrypted_key: &[u8], passphrase: SecretString) -> Re
             encrypted_key_size = encrypted_key.len(),
             "Failed to create age decryptor - invalid encrypted key format"
         );
-        CryptoError::DecryptionFailed(e.to_string())
+        damaged_key_file(encrypted_key).unwrap_or(CryptoError::DecryptionFailed(e.to_string()))
     })?;
 
     let mut decrypted = Vec::new();
@@ -72,7 +83,7 @@ pub fn decrypt_private_key(encrypted_key: &[u8], passphrase: SecretString) -> Re
         .decrypt(std::iter::once(&identity as &dyn age::Identity))
         .map_err(|e| {
             debug!("Passphrase validation failed during age decryption: {}", e);
-            CryptoError::WrongPassphrase
+            damaged_key_file(encrypted_key).unwrap_or(CryptoError::WrongPassphrase)
         })?;
 
     debug!("Passphrase validation successful, reading decrypted private key data");
@@ -82,7 +93,7 @@ pub fn decrypt_private_key(encrypted_key: &[u8], passphrase: SecretString) -> Re
             error = %e,
             "IO error while reading decrypted private key data"
         );
-        CryptoError::IoError(e)
+        damaged_key_file(encrypted_key).unwrap_or(CryptoError::IoError(e))
     })?;
 
     debug!(
@@ -123,9 +134,63 @@ pub fn decrypt_private_key(encrypted_key: &[u8], passphrase: SecretString) -> Re
     Ok(PrivateKey::from(SecretString::from(private_key_str)))
 }
 
+/// A key file age can't read because it was damaged in transit, as opposed
+/// to a wrong passphrase
+fn damaged_key_file(encrypted_key: &[u8]) -> Option<CryptoError> {
+    let diagnosis = diagnose_key_file(encrypted_key);
+    if diagnosis.is_clean() {
+        return None;
+    }
+    warn!(issues = diagnosis.issues.len(), "Key file is damaged");
+    Some(CryptoError::InvalidKeyFormat(diagnosis.summary()))
+}
+
+/// Protect a private key with a typed passphrase under the current
+/// normalization policy
+pub fn wrap_private_key(private_key: &PrivateKey, passphrase: &str) -> Result<Vec<u8>> {
+    let normalized = normalize_passphrase(passphrase);
+    if normalized.is_empty() {
+        return Err(CryptoError::EncryptionFailed(
+            "Passphrase is empty after removing surrounding spaces".to_string(),
+        ));
+    }
+    encrypt_private_key(private_key, SecretString::from(normalized.to_string()))
+}
+
+/// Unlock a key file with a typed passphrase
+///
+/// `policy` is the normalization policy the key was protected under. Keys
+/// from before normalization try the input as typed, then normalized.
+pub fn unwrap_private_key(
+    encrypted_key: &[u8],
+    passphrase: &str,
+    policy: u32,
+) -> Result<UnlockedPrivateKey> {
+    let mut candidates = passphrase_candidates(passphrase, policy);
+    candidates.retain(|candidate| !candidate.is_empty());
+    let attempts = candidates.len();
+    for (attempt, candidate) in candidates.iter().enumerate() {
+        match decrypt_private_key(encrypted_key, SecretString::from(candidate.to_string())) {
+            Ok(private_key) => {
+                debug!(policy, attempt, "Unlocked passphrase key");
+                return Ok(UnlockedPrivateKey {
+                    private_key,
+                    needs_rewrap: needs_rewrap(policy),
+                });
+            }
+            Err(CryptoError::WrongPassphrase) if attempt + 1 < attempts => continue,
+            Err(e) => return Err(e),
+        }
+    }
+    Err(CryptoError::WrongPassphrase)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::services::key_management::passphrase::domain::{
+        LEGACY_PASSPHRASE_POLICY, PASSPHRASE_POLICY_VERSION,
+    };
 
     #[test]
     fn test_generate_keypair() {
@@ -164,4 +229,61 @@ fn test_decrypt_with_wrong_passphrase() {
             assert!(matches!(e, CryptoError::WrongPassphrase));
         }
     }
+
+    #[test]
+    fn test_wrapped_key_unlocks_with_nfd_or_pasted_spaces() {
+        let keypair = generate_keypair().unwrap();
+        let encrypted = wrap_private_key(&keypair.private_key, "Caf\u{e9} secret 1!").unwrap();
+
+        for typed in [
+            "Cafe\u{301} secret 1!",
+            "Caf\u{e9}\u{00A0}secret 1! ",
+            " Caf\u{e9} secret 1!\n",
+        ] {
+            let unlocked =
+                unwrap_private_key(&encrypted, typed, PASSPHRASE_POLICY_VERSION).unwrap();
+            assert_eq!(
+                unlocked.private_key.expose_secret(),
+                keypair.private_key.expose_secret()
+            );
+            assert!(!unlocked.needs_rewrap);
+        }
+
+        assert!(matches!(
+            unwrap_private_key(&encrypted, "Caf secret 1!", PASSPHRASE_POLICY_VERSION),
+            Err(CryptoError::WrongPassphrase)
+        ));
+    }
+
+    #[test]
+    fn test_legacy_key_falls_back_and_flags_rewrap() {
+        let keypair = generate_keypair().unwrap();
+
+        // Created before normalization, with a trailing space kept as typed
+        let raw = SecretString::from("Legacy secret 1! ".to_string());
+        let legacy = encrypt_private_key(&keypair.private_key, raw).unwrap();
+        let unlocked =
+            unwrap_private_key(&legacy, "Legacy secret 1! ", LEGACY_PASSPHRASE_POLICY).unwrap();
+        assert!(unlocked.needs_rewrap);
+        assert!(matches!(
+            unwrap_private_key(&legacy, "Legacy secret 1!", LEGACY_PASSPHRASE_POLICY),
+            Err(CryptoError::WrongPassphrase)
+        ));
+
+        // Created before normalization from an already-normalized paste
+        let nfc = SecretString::from("Caf\u{e9} 1!".to_string());
+        let legacy = encrypt_private_key(&keypair.private_key, nfc).unwrap();
+        let unlocked =
+            unwrap_private_key(&legacy, "Cafe\u{301} 1!", LEGACY_PASSPHRASE_POLICY).unwrap();
+        assert!(unlocked.needs_rewrap);
+
+        // Re-wrapping moves the key to the current policy
+        let rewrapped = wrap_private_key(&unlocked.private_key, "Cafe\u{301} 1!").unwrap();
+        let unlocked =
+            unwrap_private_key(&rewrapped, "Caf\u{e9} 1! ", PASSPHRASE_POLICY_VERSION).unwrap();
+        assert_eq!(
+            unlocked.private_key.expose_secret(),
+            keypair.private_key.expose_secret()
+        );
+    }
 }
@@ -1,5 +1,8 @@
 pub mod key_derivation;
 pub mod storage;
 
-pub use key_derivation::{decrypt_private_key, encrypt_private_key, generate_keypair};
+pub use key_derivation::{
+    UnlockedPrivateKey, decrypt_private_key, encrypt_private_key, generate_keypair,
+    unwrap_private_key, wrap_private_key,
+};
 pub use storage::{PassphraseKeyRepository, StorageError};
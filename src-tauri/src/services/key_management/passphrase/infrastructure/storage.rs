@@ -1,9 +1,14 @@
+use crate::error::StorageError as SharedStorageError;
+use crate::services::key_management::passphrase::domain::PASSPHRASE_POLICY_VERSION;
 use crate::services::key_management::shared::domain::models::key_lifecycle::{
     KeyLifecycleStatus, StatusHistoryEntry,
 };
+use crate::services::key_management::shared::domain::models::key_location::KeyFileLocation;
 use crate::services::key_management::shared::infrastructure::{
-    KeyEntry, KeyRegistry, load_encrypted_key, save_encrypted_key,
+    KeyEntry, KeyRegistry, load_encrypted_key, load_passphrase_key_file, save_encrypted_key,
 };
+use crate::services::shared::infrastructure::io::atomic_write_sync;
+use crate::services::shared::infrastructure::{ChangeEvent, publish};
 use chrono::Utc;
 use std::path::PathBuf;
 
@@ -16,6 +21,7 @@ pub enum StorageError {
     KeyNotFound(String),
     KeyFileNotFound(String),
     KeyFileLoadFailed(String),
+    KeyMediaNotPresent(String),
     KeySaveFailed(String),
 }
 
@@ -27,6 +33,7 @@ fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
             Self::KeyNotFound(key_id) => write!(f, "Key '{}' not found in registry", key_id),
             Self::KeyFileNotFound(filename) => write!(f, "Key file '{}' not found", filename),
             Self::KeyFileLoadFailed(msg) => write!(f, "Failed to load key file: {}", msg),
+            Self::KeyMediaNotPresent(label) => write!(f, "Key drive '{}' is not connected", label),
             Self::KeySaveFailed(msg) => write!(f, "Failed to save key: {}", msg),
         }
     }
@@ -50,6 +57,16 @@ pub fn load_encrypted_key(filename: &str) -> Result<Vec<u8>> {
         load_encrypted_key(filename).map_err(|e| StorageError::KeyFileLoadFailed(e.to_string()))
     }
 
+    /// Load a key file, following its location if it was relinked
+    pub fn load_key_file(filename: &str, location: Option<&KeyFileLocation>) -> Result<Vec<u8>> {
+        load_passphrase_key_file(filename, location).map_err(|e| match e {
+            SharedStorageError::KeyMediaNotPresent { volume_label } => {
+                StorageError::KeyMediaNotPresent(volume_label)
+            }
+            e => StorageError::KeyFileLoadFailed(e.to_string()),
+        })
+    }
+
     pub fn register_key(
         key_id: String,
         label: String,
@@ -59,6 +76,10 @@ pub fn register_key(
         let mut registry =
             KeyRegistry::load().map_err(|e| StorageError::RegistryLoadFailed(e.to_string()))?;
 
+        let event = ChangeEvent::KeyAdded {
+            key_id: key_id.clone(),
+            label: label.clone(),
+        };
         let entry = KeyEntry::Passphrase {
             label,
             created_at: Utc::now(),
@@ -74,6 +95,8 @@ pub fn register_key(
             vault_associations: vec![],
             deactivated_at: None,
             previous_lifecycle_status: None,
+            key_location: None,
+            passphrase_policy: PASSPHRASE_POLICY_VERSION,
         };
 
         registry
@@ -83,10 +106,39 @@ pub fn register_key(
         registry
             .save()
             .map_err(|e| StorageError::RegistrySaveFailed(e.to_string()))?;
+        publish(event);
 
         Ok(())
     }
 
+    /// Replace a key file with one protected under the current passphrase
+    /// normalization policy, and record the policy
+    ///
+    /// The file is written in place, following a relinked location.
+    pub fn rewrap_key_file(key_id: &str, encrypted_key: &[u8]) -> Result<()> {
+        let mut registry =
+            KeyRegistry::load().map_err(|e| StorageError::RegistryLoadFailed(e.to_string()))?;
+        let key_path = registry
+            .get_passphrase_key_path(key_id)
+            .map_err(|e| StorageError::KeyFileNotFound(e.to_string()))?;
+
+        atomic_write_sync(&key_path, encrypted_key)
+            .map_err(|e| StorageError::KeySaveFailed(e.to_string()))?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(&key_path, std::fs::Permissions::from_mode(0o600))
+                .map_err(|e| StorageError::KeySaveFailed(e.to_string()))?;
+        }
+
+        registry
+            .set_passphrase_policy(key_id, PASSPHRASE_POLICY_VERSION)
+            .map_err(StorageError::KeyNotFound)?;
+        registry
+            .save()
+            .map_err(|e| StorageError::RegistrySaveFailed(e.to_string()))
+    }
+
     pub fn get_key(key_id: &str) -> Result<KeyEntry> {
         let registry =
             KeyRegistry::load().map_err(|e| StorageError::RegistryLoadFailed(e.to_string()))?;
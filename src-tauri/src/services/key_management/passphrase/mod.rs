@@ -15,10 +15,14 @@
 pub mod state;
 
 pub use application::{
-    GeneratedKey, GenerationError, PassphraseManager, ValidationError, VaultIntegrationError,
+    CreatedRecoveryShares, GeneratedKey, GenerationError, HeldRecoveryShares, PassphraseManager,
+    RecoveryShareError, UnlockService, ValidationError, VaultIntegrationError,
+};
+pub use domain::{
+    PassphraseError, PassphraseStrength, ValidationResult, calculate_strength_score,
+    normalize_passphrase,
 };
-pub use domain::{PassphraseError, PassphraseStrength, ValidationResult, calculate_strength_score};
 pub use infrastructure::{
     PassphraseKeyRepository, StorageError, decrypt_private_key, encrypt_private_key,
-    generate_keypair,
+    generate_keypair, unwrap_private_key, wrap_private_key,
 };
@@ -1,10 +1,20 @@
-use super::services::{KeyManagementError, KeyRegistryService, UnifiedKeyListService};
+use super::services::{
+    KeyManagementError, KeyRegistryService, KeyRemovalPlannerService, UnifiedKeyListService,
+    UnlockKey, YubiKeyReplacementService,
+};
 use crate::prelude::*;
 use crate::services::key_management::shared::KeyEntry;
 use crate::services::key_management::shared::domain::models::key_lifecycle::KeyLifecycleStatus;
 use crate::services::key_management::shared::domain::models::key_reference::{
     GlobalKey, KeyListFilter,
 };
+use crate::services::key_management::shared::domain::models::key_removal::KeyRemovalPlan;
+use crate::services::key_management::shared::domain::models::key_replacement::{
+    VaultSelection, YubiKeyReplacementReport,
+};
+use crate::services::shared::infrastructure::{
+    ChangeEvent, MutationJournal, ProgressManager, apply_and_publish,
+};
 
 pub type Result<T> = std::result::Result<T, KeyManagementError>;
 
@@ -18,6 +28,8 @@
 pub struct KeyManager {
     registry_service: KeyRegistryService,
     unified_list_service: UnifiedKeyListService,
+    replacement_service: YubiKeyReplacementService,
+    removal_planner: KeyRemovalPlannerService,
 }
 
 impl KeyManager {
@@ -25,6 +37,8 @@ pub fn new() -> Self {
         Self {
             registry_service: KeyRegistryService::new(),
             unified_list_service: UnifiedKeyListService::new(),
+            replacement_service: YubiKeyReplacementService::new(),
+            removal_planner: KeyRemovalPlannerService::new(),
         }
     }
 
@@ -46,6 +60,17 @@ pub fn update_key(&self, key_id: &str, updated_entry: KeyEntry) -> Result<()> {
         self.registry_service.update_key(key_id, updated_entry)
     }
 
+    /// Update a key entry together with the vault manifest that references it
+    pub fn update_key_with_manifest(
+        &self,
+        key_id: &str,
+        updated_entry: KeyEntry,
+        metadata: &crate::services::vault::VaultMetadata,
+    ) -> Result<()> {
+        self.registry_service
+            .update_key_with_manifest(key_id, updated_entry, metadata)
+    }
+
     /// Detach a key from a vault
     pub async fn detach_key_from_vault(&self, key_id: &str, vault_id: &str) -> Result<()> {
         self.registry_service
@@ -53,6 +78,33 @@ pub async fn detach_key_from_vault(&self, key_id: &str, vault_id: &str) -> Resul
             .await
     }
 
+    /// Replace a lost YubiKey with the connected replacement
+    ///
+    /// Re-encrypts the latest archive of each selected vault without the lost
+    /// key, moves the vaults to the replacement and revokes the lost key.
+    pub async fn replace_yubikey(
+        &self,
+        lost_key_id: &str,
+        selection: &VaultSelection,
+        unlock: &UnlockKey,
+        progress: &mut ProgressManager,
+    ) -> Result<YubiKeyReplacementReport> {
+        self.replacement_service
+            .replace(lost_key_id, selection, unlock, progress)
+            .await
+    }
+
+    /// Which recorded archives removing a key now would orphan or degrade
+    pub fn plan_key_removal(&self, key_id: &str) -> Result<KeyRemovalPlan> {
+        self.removal_planner.plan(key_id)
+    }
+
+    /// Check that removing a key was confirmed with its current plan's hash,
+    /// needed whenever archives would be orphaned
+    pub fn confirm_key_removal(&self, key_id: &str, plan_hash: Option<&str>) -> Result<()> {
+        self.removal_planner.confirm(key_id, plan_hash).map(|_| ())
+    }
+
     /// Get all passphrase keys for a specific vault
     pub async fn get_vault_passphrase_keys(
         &self,
@@ -170,9 +222,7 @@ pub async fn attach_key_to_vault(
         vault_id: &str,
     ) -> std::result::Result<(), Box<dyn std::error::Error>> {
         use crate::services::vault::VaultManager;
-        use crate::services::vault::infrastructure::persistence::metadata::{
-            RecipientInfo, RecipientType,
-        };
+        use crate::services::vault::infrastructure::persistence::metadata::RecipientType;
 
         // Load registry and get the key
         let mut registry = self.registry_service.load_registry()?;
@@ -234,60 +284,8 @@ pub async fn attach_key_to_vault(
         }
 
         // Create recipient info based on key type
-        let recipient = match &key_entry {
-            KeyEntry::Passphrase {
-                label,
-                public_key,
-                key_filename,
-                created_at,
-                ..
-            } => RecipientInfo {
-                key_id: key_id.to_string(),
-                recipient_type: RecipientType::Passphrase {
-                    key_filename: key_filename.clone(),
-                },
-                public_key: public_key.clone(),
-                label: label.clone(),
-                created_at: *created_at,
-            },
-            KeyEntry::Yubikey {
-                label,
-                recipient,
-                serial,
-                slot,
-                piv_slot,
-                identity_tag,
-                model,
-                firmware_version,
-                created_at,
-                ..
-            } => RecipientInfo {
-                key_id: key_id.to_string(),
-                recipient_type: RecipientType::YubiKey {
-                    serial: serial.clone(),
-                    slot: *slot,
-                    piv_slot: *piv_slot,
-                    model: model.clone(),
-                    identity_tag: identity_tag.clone(),
-                    firmware_version: firmware_version.clone(),
-                },
-                public_key: recipient.clone(),
-                label: label.clone(),
-                created_at: *created_at,
-            },
-            KeyEntry::Recipient {
-                label,
-                public_key,
-                created_at,
-                ..
-            } => RecipientInfo {
-                key_id: key_id.to_string(),
-                recipient_type: RecipientType::PublicKeyOnly,
-                public_key: public_key.clone(),
-                label: label.clone(),
-                created_at: *created_at,
-            },
-        };
+        let recipient = KeyRegistryService::key_entry_to_recipient(key_id, &key_entry)
+            .ok_or("A reserved YubiKey slot can't be attached; register its YubiKey instead")?;
 
         // Add recipient to vault metadata
         metadata.add_recipient(recipient);
@@ -304,13 +302,23 @@ pub async fn attach_key_to_vault(
         // Add vault association (multi-vault support)
         key_entry.add_vault_association(vault_id.to_string());
 
-        // Save updated registry
         registry.update_key(key_id, key_entry)?;
-        registry.save().map_err(|e| e.to_string())?;
 
-        // Save updated vault metadata
-        let service = crate::services::vault::application::services::VaultMetadataService::new();
-        service.save_manifest(&metadata)?;
+        // Registry and manifest must change together - journal both writes
+        let writes = vec![
+            registry.to_pending_write().map_err(|e| e.to_string())?,
+            crate::services::vault::vault_pending_write(&metadata).map_err(|e| e.to_string())?,
+        ];
+        crate::services::vault::infrastructure::persistence::snapshot_before("attach_key_to_vault");
+        apply_and_publish(
+            &MutationJournal::open()?,
+            "attach_key_to_vault",
+            writes,
+            vec![ChangeEvent::KeyAttached {
+                key_id: key_id.to_string(),
+                vault_id: vault_id.to_string(),
+            }],
+        )?;
 
         Ok(())
     }
@@ -6,4 +6,4 @@
 pub mod services;
 
 pub use manager::KeyManager;
-pub use services::{KeyManagementError, KeyRegistryService, UnifiedKeyListService};
+pub use services::{ContactService, KeyManagementError, KeyRegistryService, UnifiedKeyListService};
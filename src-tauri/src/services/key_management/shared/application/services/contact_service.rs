@@ -0,0 +1,95 @@
+//! Contact Service
+//!
+//! Add, list and remove contacts: other people's recipients the user can
+//! encrypt to alongside their own vault keys.
+
+use super::registry_service::{KeyManagementError, KeyRegistryService, Result};
+use crate::prelude::*;
+use crate::services::key_management::shared::domain::models::contact::{
+    Contact, ContactInfo, generate_contact_id,
+};
+use crate::services::shared::infrastructure::{ChangeEvent, publish};
+use chrono::Utc;
+
+#[derive(Debug, Default)]
+pub struct ContactService {
+    registry_service: KeyRegistryService,
+}
+
+impl ContactService {
+    pub fn new() -> Self {
+        Self {
+            registry_service: KeyRegistryService::new(),
+        }
+    }
+
+    /// Validate and save a new contact
+    #[instrument(skip(self, recipient, note))]
+    pub fn add_contact(
+        &self,
+        name: &str,
+        recipient: &str,
+        note: Option<&str>,
+    ) -> Result<ContactInfo> {
+        let contact = Contact::new(name, recipient, note, Utc::now())?;
+        let contact_id = generate_contact_id(&contact.name, contact.added_at);
+        let info = ContactInfo::new(&contact_id, &contact);
+
+        let mut registry = self.registry_service.load_registry()?;
+        registry.add_contact(contact_id.clone(), contact)?;
+        registry.save().map_err(|e| {
+            error!(error = %e, "Failed to save registry after adding contact");
+            KeyManagementError::RegistrySaveFailed(e.to_string())
+        })?;
+        publish(ChangeEvent::ContactAdded {
+            contact_id: contact_id.clone(),
+            name: info.name.clone(),
+        });
+
+        info!(contact_id = %contact_id, "Contact added");
+        Ok(info)
+    }
+
+    /// All contacts, sorted by name
+    #[instrument(skip(self))]
+    pub fn list_contacts(&self) -> Result<Vec<ContactInfo>> {
+        let registry = self.registry_service.load_registry()?;
+        let mut contacts: Vec<ContactInfo> = registry
+            .contacts
+            .iter()
+            .map(|(id, contact)| ContactInfo::new(id, contact))
+            .collect();
+        contacts.sort_by_key(|contact| contact.name.to_lowercase());
+        Ok(contacts)
+    }
+
+    /// Remove a contact; archives already encrypted to it are unaffected
+    #[instrument(skip(self))]
+    pub fn remove_contact(&self, contact_id: &str) -> Result<()> {
+        let mut registry = self.registry_service.load_registry()?;
+        registry.remove_contact(contact_id)?;
+        registry.save().map_err(|e| {
+            error!(error = %e, "Failed to save registry after removing contact");
+            KeyManagementError::RegistrySaveFailed(e.to_string())
+        })?;
+        publish(ChangeEvent::ContactRemoved {
+            contact_id: contact_id.to_string(),
+        });
+
+        info!(contact_id = %contact_id, "Contact removed");
+        Ok(())
+    }
+
+    /// Look up the contacts chosen for an encryption, in the order given
+    pub fn resolve(&self, contact_ids: &[String]) -> Result<Vec<ContactInfo>> {
+        if contact_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+        let registry = self.registry_service.load_registry()?;
+        Ok(registry
+            .resolve_contacts(contact_ids)?
+            .into_iter()
+            .map(|(id, contact)| ContactInfo::new(id, contact))
+            .collect())
+    }
+}
@@ -3,12 +3,16 @@
 //! Service for importing external .enc key files into the key registry.
 //! Supports both passphrase and YubiKey metadata import with comprehensive validation.
 
+use crate::services::crypto::infrastructure::CryptoError;
+use crate::services::key_management::passphrase::domain::{
+    LEGACY_PASSPHRASE_POLICY, diagnose_key_file,
+};
+use crate::services::key_management::passphrase::infrastructure::unwrap_private_key;
 use crate::services::key_management::shared::domain::models::key_lifecycle::KeyLifecycleStatus;
 use crate::services::key_management::shared::domain::models::key_reference::VaultKey;
 use crate::services::key_management::shared::infrastructure::{KeyEntry, KeyRegistry};
 use crate::services::shared::infrastructure::path_management::get_keys_dir;
-use crate::services::shared::infrastructure::sanitize_label;
-use age::secrecy::SecretString;
+use crate::services::shared::infrastructure::{ChangeEvent, publish, sanitize_label};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::fs;
@@ -67,6 +71,7 @@ pub enum ImportWarning {
     OldKeyFile { age_days: i64 },
     UntrustedSource,
     LabelSanitized { original: String, sanitized: String },
+    LabelRenamed { original: String, renamed: String },
 }
 
 impl std::fmt::Display for ImportWarning {
@@ -85,6 +90,13 @@ fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
             } => {
                 write!(f, "Label sanitized from '{}' to '{}'", original, sanitized)
             }
+            ImportWarning::LabelRenamed { original, renamed } => {
+                write!(
+                    f,
+                    "Label '{}' is already in use; imported as '{}'",
+                    original, renamed
+                )
+            }
         }
     }
 }
@@ -98,6 +110,10 @@ pub enum ImportError {
     #[error("Invalid file format: {0}")]
     InvalidFormat(String),
 
+    /// Damaged in transit, e.g. by a text editor; may be repairable
+    #[error("{0}")]
+    DamagedKeyFile(String),
+
     #[error("Decryption failed: {0}")]
     DecryptionFailed(String),
 
@@ -124,6 +140,12 @@ pub enum ImportError {
 
     #[error("Duplicate key: {0}")]
     DuplicateKey(String),
+
+    #[error("Label '{label}' is already used by key '{existing_key_id}'")]
+    LabelConflict {
+        label: String,
+        existing_key_id: String,
+    },
 }
 
 impl KeyImportService {
@@ -133,6 +155,10 @@ pub fn new() -> Self {
     }
 
     /// Import a key file with comprehensive validation
+    ///
+    /// When the label is already used by another key, `auto_rename` imports it
+    /// as `Label (2)` (or the next free suffix); otherwise the import fails
+    /// with `ImportError::LabelConflict` so the caller can prompt for a label.
     pub async fn import_key_file(
         &self,
         file_path: &str,
@@ -140,6 +166,7 @@ pub async fn import_key_file(
         override_label: Option<String>,
         attach_to_vault: Option<String>,
         validate_only: bool,
+        auto_rename: bool,
     ) -> Result<(VaultKey, ValidationStatus, Vec<String>), ImportError> {
         debug!(
             file_path = %file_path,
@@ -147,6 +174,7 @@ pub async fn import_key_file(
             override_label = ?override_label,
             attach_to_vault = ?attach_to_vault,
             validate_only = validate_only,
+            auto_rename = auto_rename,
             "Starting key import process"
         );
 
@@ -183,35 +211,30 @@ pub async fn import_key_file(
         // Step 2: Read and parse the .enc file
         let encrypted_content = fs::read(path)?;
 
-        // Step 3: Validate age format
-        let decryptor = age::Decryptor::new(&encrypted_content[..])
+        // Step 3: Validate age format, telling damage such as CRLF line
+        // endings apart from files that were never key files
+        let diagnosis = diagnose_key_file(&encrypted_content);
+        if !diagnosis.is_clean() {
+            warn!(
+                issues = diagnosis.issues.len(),
+                repairable = diagnosis.repairable,
+                "Import blocked: key file is damaged"
+            );
+            return Err(ImportError::DamagedKeyFile(diagnosis.summary()));
+        }
+        age::Decryptor::new(&encrypted_content[..])
             .map_err(|e| ImportError::InvalidFormat(format!("Not a valid age file: {}", e)))?;
 
         // Step 4: Try to decrypt with passphrase if provided
         let (key_metadata, private_key_data) = if let Some(pass) = passphrase {
-            // Try passphrase decryption
-            let secret_pass = SecretString::from(pass);
-            let identity = age::scrypt::Identity::new(secret_pass.clone());
-
-            match decryptor.decrypt(std::iter::once(&identity as &dyn age::Identity)) {
-                Ok(mut reader) => {
-                    // Successfully decrypted with passphrase
-                    let mut decrypted = Vec::new();
-                    std::io::copy(&mut reader, &mut decrypted)?;
-
-                    // Parse the decrypted private key
-                    let private_key_str = String::from_utf8(decrypted)
-                        .map_err(|e| ImportError::InvalidKeyData(e.to_string()))?;
-
-                    // Validate it's an age private key
-                    if !private_key_str.starts_with("AGE-SECRET-KEY-") {
-                        return Err(ImportError::InvalidKeyData(
-                            "Decrypted data is not a valid age private key".to_string(),
-                        ));
-                    }
+            // Try passphrase decryption; the file's normalization policy is
+            // unknown, so the passphrase is tried as typed and then normalized
+            match unwrap_private_key(&encrypted_content, &pass, LEGACY_PASSPHRASE_POLICY) {
+                Ok(unlocked) => {
+                    let private_key_str = unlocked.private_key.expose_secret();
 
                     // Derive public key from private key
-                    let identity = age::x25519::Identity::from_str(&private_key_str)
+                    let identity = age::x25519::Identity::from_str(private_key_str)
                         .map_err(|e| ImportError::InvalidKeyData(e.to_string()))?;
                     let public_key = identity.to_public().to_string();
 
@@ -226,6 +249,9 @@ pub async fn import_key_file(
                         Some(encrypted_content),
                     )
                 }
+                Err(CryptoError::InvalidKeyFormat(e)) => {
+                    return Err(ImportError::InvalidKeyData(e));
+                }
                 Err(e) => {
                     // Decryption failed - could be wrong passphrase or not a passphrase key
                     debug!("Failed to decrypt with passphrase: {}", e);
@@ -269,21 +295,7 @@ pub async fn import_key_file(
             )
         };
 
-        // Step 5: Sanitize label using shared function (matches normal key creation)
-        let sanitized = sanitize_label(&key_metadata.label)
-            .map_err(|e| ImportError::InvalidKeyData(format!("Failed to sanitize label: {}", e)))?;
-
-        if sanitized.sanitized != key_metadata.label {
-            warnings.push(
-                ImportWarning::LabelSanitized {
-                    original: key_metadata.label.clone(),
-                    sanitized: sanitized.sanitized.clone(),
-                }
-                .to_string(),
-            );
-        }
-
-        // Step 6: Check for duplicates in registry (BLOCK duplicate public keys)
+        // Step 5: Check for duplicates in registry (BLOCK duplicate public keys)
         let mut registry =
             KeyRegistry::load().map_err(|e| ImportError::RegistryError(e.to_string()))?;
 
@@ -308,6 +320,45 @@ pub async fn import_key_file(
             )));
         }
 
+        // Step 6: Enforce label uniqueness (auto-suffix or report the conflict)
+        let label = registry
+            .resolve_label(&key_metadata.label, auto_rename)
+            .map_err(|conflict| {
+                warn!(
+                    label = %conflict.label,
+                    existing_key_id = %conflict.existing_key_id,
+                    "Import blocked: label already in use"
+                );
+                ImportError::LabelConflict {
+                    label: conflict.label,
+                    existing_key_id: conflict.existing_key_id,
+                }
+            })?;
+
+        if label != key_metadata.label.trim() {
+            warnings.push(
+                ImportWarning::LabelRenamed {
+                    original: key_metadata.label.clone(),
+                    renamed: label.clone(),
+                }
+                .to_string(),
+            );
+        }
+
+        // Step 6a: Sanitize label using shared function (matches normal key creation)
+        let sanitized = sanitize_label(&label)
+            .map_err(|e| ImportError::InvalidKeyData(format!("Failed to sanitize label: {}", e)))?;
+
+        if sanitized.sanitized != label {
+            warnings.push(
+                ImportWarning::LabelSanitized {
+                    original: label.clone(),
+                    sanitized: sanitized.sanitized.clone(),
+                }
+                .to_string(),
+            );
+        }
+
         // Step 7: Check file age
         let file_age_days = (Utc::now() - key_metadata.created_at).num_days();
         if file_age_days > 365 {
@@ -401,7 +452,7 @@ pub async fn import_key_file(
                     }
 
                     KeyEntry::Passphrase {
-                        label: key_metadata.label.clone(), // Original label (display version)
+                        label: label.clone(), // Display version (may carry a collision suffix)
                         created_at: key_metadata.created_at,
                         last_used: None,
                         public_key: key_metadata.public_key.clone(),
@@ -417,6 +468,8 @@ pub async fn import_key_file(
                         vault_associations: vec![],
                         deactivated_at: None,
                         previous_lifecycle_status: None,
+                        key_location: None,
+                        passphrase_policy: LEGACY_PASSPHRASE_POLICY,
                     }
                 } else {
                     return Err(ImportError::InvalidKeyData(
@@ -433,7 +486,7 @@ pub async fn import_key_file(
                 firmware_version,
             } => {
                 KeyEntry::Yubikey {
-                    label: key_metadata.label.clone(), // Original label (display version)
+                    label: label.clone(), // Display version (may carry a collision suffix)
                     created_at: key_metadata.created_at,
                     last_used: None,
                     serial,
@@ -455,6 +508,7 @@ pub async fn import_key_file(
                     vault_associations: vec![],
                     deactivated_at: None,
                     previous_lifecycle_status: None,
+                    last_reader: None,
                 }
             }
         };
@@ -467,10 +521,14 @@ pub async fn import_key_file(
         registry
             .save()
             .map_err(|e| ImportError::RegistryError(e.to_string()))?;
+        publish(ChangeEvent::KeyAdded {
+            key_id: key_id.clone(),
+            label: key_entry.label().to_string(),
+        });
 
         info!(
             key_id = %key_id,
-            label = %key_metadata.label,
+            label = %label,
             "Successfully imported key"
         );
 
@@ -546,6 +604,7 @@ fn get_public_key(&self, entry: &KeyEntry) -> String {
             KeyEntry::Passphrase { public_key, .. } => public_key.clone(),
             KeyEntry::Yubikey { recipient, .. } => recipient.clone(),
             KeyEntry::Recipient { public_key, .. } => public_key.clone(),
+            KeyEntry::YubikeyPending { .. } => String::new(),
         }
     }
 }
@@ -0,0 +1,455 @@
+//! Key Removal Planner Service
+//!
+//! Works out which archives a key's removal would leave unreadable. Every
+//! archive in every vault's index is classified by the recipients recorded
+//! with it. Archives recorded before recipients were kept fall back to the
+//! age header of the archive file, which only works for the encryption the
+//! file still holds; earlier ones are reported as unknown.
+//!
+//! X25519 header stanzas are anonymous, so passphrase keys are matched
+//! against the vault's manifest the same way the decrypt-time recipient
+//! check does (see `KeyRecipientCheckService`).
+
+use super::registry_service::{KeyManagementError, KeyRegistryService, Result};
+use crate::prelude::*;
+use crate::services::crypto::application::services::{KeyRecipientCheck, KeyRecipientCheckService};
+use crate::services::crypto::infrastructure::read_age_header_file;
+use crate::services::key_management::shared::KeyEntry;
+use crate::services::key_management::shared::domain::models::key_removal::{
+    ArchiveRemovalImpact, KeyRemovalPlan, RecipientSource, RemovalImpact, VaultRemovalImpact,
+};
+use crate::services::shared::infrastructure::get_vaults_directory;
+use crate::services::vault::domain::models::ArchiveIndexEntry;
+use crate::services::vault::infrastructure::persistence::metadata::VaultMetadata;
+use crate::services::vault::infrastructure::persistence::{ArchiveIndex, find_vault_sync};
+use crate::types::ByteSize;
+use std::path::Path;
+
+/// Service planning the removal of a key
+#[derive(Debug)]
+pub struct KeyRemovalPlannerService {
+    registry_service: KeyRegistryService,
+}
+
+impl KeyRemovalPlannerService {
+    pub fn new() -> Self {
+        Self {
+            registry_service: KeyRegistryService::new(),
+        }
+    }
+
+    /// Classify every recorded archive for removing `key_id` now
+    #[instrument(skip(self))]
+    pub fn plan(&self, key_id: &str) -> Result<KeyRemovalPlan> {
+        let key = self.registry_service.get_key(key_id)?;
+        let index =
+            ArchiveIndex::load().map_err(|e| KeyManagementError::StorageError(e.to_string()))?;
+        let archives_dir =
+            get_vaults_directory().map_err(|e| KeyManagementError::StorageError(e.to_string()))?;
+
+        let plan = Self::evaluate(key_id, &key, &index, &archives_dir, find_vault_sync);
+        info!(
+            key_id = %key_id,
+            orphaned = plan.orphaned,
+            degraded = plan.degraded,
+            unknown = plan.unknown,
+            recommendation = ?plan.recommendation,
+            "Planned key removal"
+        );
+        Ok(plan)
+    }
+
+    /// Plan removing `key_id` and check `plan_hash` against it
+    ///
+    /// Fails with `RemovalUnconfirmed` when archives would be orphaned and
+    /// the hash isn't the current plan's.
+    pub fn confirm(&self, key_id: &str, plan_hash: Option<&str>) -> Result<KeyRemovalPlan> {
+        let plan = self.plan(key_id)?;
+        Self::require_confirmation(&plan, plan_hash)?;
+        Ok(plan)
+    }
+
+    /// Whether `plan_hash` confirms `plan`
+    pub fn require_confirmation(plan: &KeyRemovalPlan, plan_hash: Option<&str>) -> Result<()> {
+        if plan.is_confirmed_by(plan_hash) {
+            return Ok(());
+        }
+        warn!(
+            key_id = %plan.key_id,
+            orphaned = plan.orphaned,
+            hash_given = plan_hash.is_some(),
+            "Key removal not confirmed against its current plan"
+        );
+        Err(KeyManagementError::RemovalUnconfirmed(plan.orphaned))
+    }
+
+    /// Classify the archives in `index` for removing `key`
+    ///
+    /// Archive files are looked up in `archives_dir`; `manifest_for` gives a
+    /// vault's manifest, for its name and its X25519 recipients.
+    pub fn evaluate(
+        key_id: &str,
+        key: &KeyEntry,
+        index: &ArchiveIndex,
+        archives_dir: &Path,
+        manifest_for: impl Fn(&str) -> Option<VaultMetadata>,
+    ) -> KeyRemovalPlan {
+        let mut vault_ids: Vec<&String> = index
+            .vaults
+            .iter()
+            .filter(|(_, entries)| !entries.is_empty())
+            .map(|(vault_id, _)| vault_id)
+            .collect();
+        vault_ids.sort();
+
+        let mut vaults: Vec<VaultRemovalImpact> = vault_ids
+            .into_iter()
+            .map(|vault_id| {
+                let manifest = manifest_for(vault_id);
+                let archives = index
+                    .entries(vault_id)
+                    .iter()
+                    .map(|entry| classify(key, entry, index, archives_dir, manifest.as_ref()))
+                    .collect();
+                VaultRemovalImpact {
+                    vault_id: vault_id.clone(),
+                    vault_name: manifest
+                        .as_ref()
+                        .map(|manifest| manifest.label().to_string())
+                        .unwrap_or_else(|| vault_id.clone()),
+                    archives,
+                }
+            })
+            .collect();
+        vaults.sort_by(|a, b| a.vault_name.cmp(&b.vault_name));
+
+        KeyRemovalPlan::new(key_id, key.label(), vaults)
+    }
+}
+
+impl Default for KeyRemovalPlannerService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Impact of removing `key` on the archive of `entry`
+fn classify(
+    key: &KeyEntry,
+    entry: &ArchiveIndexEntry,
+    index: &ArchiveIndex,
+    archives_dir: &Path,
+    manifest: Option<&VaultMetadata>,
+) -> ArchiveRemovalImpact {
+    let current = index
+        .current_entry(&entry.archive_name)
+        .is_some_and(|current| current.archive_id == entry.archive_id);
+    let path = archives_dir.join(&entry.archive_name);
+    let size = current
+        .then(|| std::fs::metadata(&path).ok())
+        .flatten()
+        .map(|metadata| ByteSize(metadata.len()));
+
+    let (impact, recipient_count, source) = if !entry.recipients.is_empty() {
+        let is_recipient = entry
+            .recipients
+            .iter()
+            .any(|recipient| recipient == key.public_key());
+        let count = entry.recipients.len();
+        (
+            RemovalImpact::of(is_recipient, count),
+            Some(count),
+            RecipientSource::Recorded,
+        )
+    } else if current {
+        header_impact(key, &path, manifest)
+    } else {
+        // The file's header belongs to a later encryption
+        (RemovalImpact::Unknown, None, RecipientSource::Unavailable)
+    };
+
+    ArchiveRemovalImpact {
+        archive_id: entry.archive_id.clone(),
+        archive_name: entry.archive_name.clone(),
+        created_at: entry.created_at,
+        current,
+        size,
+        impact,
+        recipient_count,
+        source,
+    }
+}
+
+/// Impact read from the archive's age header, which is all that's read
+fn header_impact(
+    key: &KeyEntry,
+    path: &Path,
+    manifest: Option<&VaultMetadata>,
+) -> (RemovalImpact, Option<usize>, RecipientSource) {
+    let header = match read_age_header_file(path) {
+        Ok(header) => header,
+        Err(e) => {
+            debug!(path = %path.display(), error = %e, "Archive header unreadable");
+            return (RemovalImpact::Unknown, None, RecipientSource::Unavailable);
+        }
+    };
+    // Encrypted straight to a passphrase, not to any key
+    if header.is_passphrase_encrypted() {
+        return (RemovalImpact::Unaffected, None, RecipientSource::Header);
+    }
+
+    let manifest_recipients: Option<Vec<&str>> = manifest.map(|manifest| {
+        manifest
+            .recipients()
+            .iter()
+            .map(|r| r.public_key.as_str())
+            .chain(manifest.contacts().iter().map(|c| c.public_key.as_str()))
+            .collect()
+    });
+    let count = header.x25519_count() + header.piv_p256_tags().len();
+    let impact = match KeyRecipientCheckService::evaluate(
+        &header,
+        key,
+        std::iter::empty(),
+        manifest_recipients.as_deref(),
+    ) {
+        KeyRecipientCheck::Recipient => RemovalImpact::of(true, count),
+        KeyRecipientCheck::NotARecipient { .. } => RemovalImpact::Unaffected,
+        KeyRecipientCheck::Unknown => RemovalImpact::Unknown,
+    };
+    (impact, Some(count), RecipientSource::Header)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::key_management::shared::domain::models::key_lifecycle::KeyLifecycleStatus;
+    use crate::services::key_management::shared::domain::models::key_removal::RemovalRecommendation;
+    use crate::services::shared::infrastructure::DeviceInfo;
+    use crate::services::vault::infrastructure::persistence::metadata::{
+        RecipientInfo, RecipientType,
+    };
+    use age::x25519::Identity;
+    use chrono::Utc;
+    use std::io::Write;
+    use tempfile::TempDir;
+
+    fn passphrase_key(public_key: &str) -> KeyEntry {
+        KeyEntry::Passphrase {
+            label: "Laptop".to_string(),
+            created_at: Utc::now(),
+            last_used: None,
+            public_key: public_key.to_string(),
+            key_filename: "laptop.agekey.enc".to_string(),
+            lifecycle_status: KeyLifecycleStatus::Active,
+            status_history: vec![],
+            vault_associations: vec![],
+            deactivated_at: None,
+            previous_lifecycle_status: None,
+            key_location: None,
+            passphrase_policy: 0,
+        }
+    }
+
+    fn entry(
+        vault_id: &str,
+        archive_id: &str,
+        name: &str,
+        recipients: &[&str],
+    ) -> ArchiveIndexEntry {
+        ArchiveIndexEntry {
+            archive_id: archive_id.to_string(),
+            vault_id: vault_id.to_string(),
+            archive_name: name.to_string(),
+            encryption_revision: 1,
+            created_at: Utc::now(),
+            file_count: 1,
+            comment: None,
+            comment_updated_at: None,
+            immutable: false,
+            parity: None,
+            migrated_from: None,
+            prune_marked: false,
+            remapped_from: None,
+            archive_sha256: None,
+            recipients: recipients.iter().map(|r| r.to_string()).collect(),
+        }
+    }
+
+    fn public_key() -> String {
+        Identity::generate().to_public().to_string()
+    }
+
+    fn encrypt_to(path: &Path, recipients: &[&str]) {
+        let parsed: Vec<age::x25519::Recipient> =
+            recipients.iter().map(|r| r.parse().unwrap()).collect();
+        let encryptor =
+            age::Encryptor::with_recipients(parsed.iter().map(|r| r as &dyn age::Recipient))
+                .unwrap();
+        let mut writer = encryptor
+            .wrap_output(std::fs::File::create(path).unwrap())
+            .unwrap();
+        writer.write_all(&[7u8; 4096]).unwrap();
+        writer.finish().unwrap();
+    }
+
+    fn manifest(recipients: &[&str]) -> VaultMetadata {
+        let device_info = DeviceInfo {
+            machine_id: "machine-1".to_string(),
+            machine_label: "laptop".to_string(),
+            created_at: Utc::now(),
+            app_version: "2.0.0".to_string(),
+        };
+        let recipients = recipients
+            .iter()
+            .map(|public_key| RecipientInfo {
+                key_id: public_key.to_string(),
+                recipient_type: RecipientType::Passphrase {
+                    key_filename: "key.agekey.enc".to_string(),
+                },
+                public_key: public_key.to_string(),
+                label: "Key".to_string(),
+                created_at: Utc::now(),
+            })
+            .collect();
+        VaultMetadata::new(
+            "vault-001".to_string(),
+            "Family".to_string(),
+            None,
+            "Family".to_string(),
+            &device_info,
+            None,
+            recipients,
+            vec![],
+            0,
+            0,
+        )
+    }
+
+    #[test]
+    fn test_each_classification_from_recorded_recipients() {
+        let (key, other) = (public_key(), public_key());
+        let mut index = ArchiveIndex::default();
+        index.record(entry("vault-a", "a1", "Taxes.age", &[&key]));
+        index.record(entry("vault-b", "b1", "Photos.age", &[&key, &other]));
+        index.record(entry("vault-c", "c1", "Shared.age", &[&other]));
+
+        let plan = KeyRemovalPlannerService::evaluate(
+            "laptop",
+            &passphrase_key(&key),
+            &index,
+            Path::new("/nonexistent"),
+            |_| None,
+        );
+
+        let impact = |vault_id: &str| {
+            let vault = plan.vaults.iter().find(|v| v.vault_id == vault_id).unwrap();
+            (vault.archives[0].impact, vault.archives[0].source)
+        };
+        assert_eq!(
+            impact("vault-a"),
+            (RemovalImpact::Orphaned, RecipientSource::Recorded)
+        );
+        assert_eq!(impact("vault-b").0, RemovalImpact::Degraded);
+        assert_eq!(impact("vault-c").0, RemovalImpact::Unaffected);
+        assert_eq!(
+            (plan.orphaned, plan.degraded, plan.unaffected, plan.unknown),
+            (1, 1, 1, 0)
+        );
+        assert_eq!(plan.recommendation, RemovalRecommendation::ReencryptFirst);
+    }
+
+    #[test]
+    fn test_legacy_archive_falls_back_to_header() {
+        let temp_dir = TempDir::new().unwrap();
+        let (key, other) = (public_key(), public_key());
+        encrypt_to(&temp_dir.path().join("Family.age"), &[&key]);
+        encrypt_to(&temp_dir.path().join("Shared.age"), &[&key, &other]);
+
+        let mut index = ArchiveIndex::default();
+        index.record(entry("vault-a", "old", "Family.age", &[]));
+        index.record(entry("vault-a", "a1", "Family.age", &[]));
+        index.record(entry("vault-b", "b1", "Shared.age", &[]));
+        // Only the latest entry for a name is in the file
+        index.find_mut("vault-a", "old").unwrap().created_at -= chrono::Duration::days(1);
+
+        let plan = KeyRemovalPlannerService::evaluate(
+            "laptop",
+            &passphrase_key(&key),
+            &index,
+            temp_dir.path(),
+            |vault_id| {
+                Some(match vault_id {
+                    "vault-a" => manifest(&[&key]),
+                    _ => manifest(&[&key, &other]),
+                })
+            },
+        );
+
+        let archives: Vec<&ArchiveRemovalImpact> =
+            plan.vaults.iter().flat_map(|v| &v.archives).collect();
+        let find = |id: &str| archives.iter().find(|a| a.archive_id == id).unwrap();
+        assert_eq!(find("a1").impact, RemovalImpact::Orphaned);
+        assert_eq!(find("a1").source, RecipientSource::Header);
+        assert_eq!(find("a1").recipient_count, Some(1));
+        assert!(find("a1").size.is_some_and(|size| size.bytes() > 4096));
+        assert_eq!(find("b1").impact, RemovalImpact::Degraded);
+        assert_eq!(find("old").impact, RemovalImpact::Unknown);
+        assert_eq!(find("old").source, RecipientSource::Unavailable);
+
+        // Without the manifest the anonymous stanzas can't be attributed
+        let plan = KeyRemovalPlannerService::evaluate(
+            "laptop",
+            &passphrase_key(&key),
+            &index,
+            temp_dir.path(),
+            |_| None,
+        );
+        assert_eq!(plan.orphaned, 0);
+        assert_eq!(plan.unknown, 3);
+        assert_eq!(
+            plan.recommendation,
+            RemovalRecommendation::CheckUnknownArchives
+        );
+    }
+
+    #[test]
+    fn test_orphaned_archives_require_the_plan_hash() {
+        let (key, other) = (public_key(), public_key());
+        let mut index = ArchiveIndex::default();
+        index.record(entry("vault-a", "a1", "Taxes.age", &[&key]));
+        let evaluate = |index: &ArchiveIndex| {
+            KeyRemovalPlannerService::evaluate(
+                "laptop",
+                &passphrase_key(&key),
+                index,
+                Path::new("/nonexistent"),
+                |_| None,
+            )
+        };
+        let plan = evaluate(&index);
+
+        assert!(matches!(
+            KeyRemovalPlannerService::require_confirmation(&plan, None),
+            Err(KeyManagementError::RemovalUnconfirmed(1))
+        ));
+        assert!(KeyRemovalPlannerService::require_confirmation(&plan, Some("stale")).is_err());
+        assert!(
+            KeyRemovalPlannerService::require_confirmation(&plan, Some(&plan.plan_hash)).is_ok()
+        );
+
+        // A new orphaned archive invalidates the hash shown earlier
+        index.record(entry("vault-a", "a2", "Taxes-2.age", &[&key]));
+        let replanned = evaluate(&index);
+        assert_ne!(replanned.plan_hash, plan.plan_hash);
+        assert!(
+            KeyRemovalPlannerService::require_confirmation(&replanned, Some(&plan.plan_hash))
+                .is_err()
+        );
+
+        // Nothing orphaned, nothing to confirm
+        let mut index = ArchiveIndex::default();
+        index.record(entry("vault-a", "a1", "Taxes.age", &[&key, &other]));
+        assert!(KeyRemovalPlannerService::require_confirmation(&evaluate(&index), None).is_ok());
+    }
+}
@@ -0,0 +1,647 @@
+//! Legacy Profile Migration Service
+//!
+//! One-time migration of profiles written by older builds (see
+//! `infrastructure::legacy` for the layouts) into the locations the
+//! PathProvider reads today. Runs during bootstrap:
+//!
+//! 1. Detect legacy profiles in the old app data directories
+//! 2. Read their keys and convert them to the current registry schema
+//! 3. Check them against the current registry and keys directory; any key
+//!    ID or key file that would be overwritten with different data is a
+//!    conflict, and nothing is written
+//! 4. Write the registry and copied key files as one journaled mutation
+//! 5. Rename the legacy files with a `.migrated` suffix
+//!
+//! Re-running is safe: migrated profiles are no longer detected, and keys
+//! already present with the same public key are skipped, so a run cut short
+//! before step 5 just finishes the renames. The last report is kept in the
+//! config directory.
+
+use super::registry_service::{KeyManagementError, Result};
+use crate::prelude::*;
+use crate::services::key_management::shared::domain::models::legacy_migration::{
+    LegacySource, MigrationConflict, MigrationReport, MigrationStatus,
+};
+use crate::services::key_management::shared::infrastructure::KeyRegistry;
+use crate::services::key_management::shared::infrastructure::legacy::{
+    LegacyKey, LegacyProfile, migrated_path,
+};
+use crate::services::shared::infrastructure::{
+    MutationJournal, PendingWrite, atomic_write_sync, get_config_dir, get_keys_dir,
+    get_legacy_app_dirs,
+};
+use chrono::Utc;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Report of the last legacy migration, in the config directory
+pub const MIGRATION_REPORT_FILENAME: &str = "legacy-migration-report.json";
+
+/// Where migrated data is written
+#[derive(Debug, Clone)]
+pub struct MigrationTargets {
+    pub keys_dir: PathBuf,
+    pub registry_path: PathBuf,
+    pub report_path: PathBuf,
+    pub journal: MutationJournal,
+}
+
+impl MigrationTargets {
+    /// The current standard locations
+    pub fn resolve() -> Result<Self> {
+        let storage_error =
+            |e: crate::error::StorageError| KeyManagementError::StorageError(e.to_string());
+        let keys_dir = get_keys_dir().map_err(storage_error)?;
+        Ok(Self {
+            registry_path: KeyRegistry::get_registry_path()
+                .map_err(|e| KeyManagementError::StorageError(e.to_string()))?,
+            keys_dir,
+            report_path: get_config_dir()
+                .map_err(storage_error)?
+                .join(MIGRATION_REPORT_FILENAME),
+            journal: MutationJournal::open().map_err(storage_error)?,
+        })
+    }
+}
+
+/// Service migrating legacy profiles into the current layout
+#[derive(Debug, Default)]
+pub struct LegacyMigrationService;
+
+impl LegacyMigrationService {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Migrate legacy profiles found in the old app data directories
+    ///
+    /// Returns `None` when there is nothing to migrate.
+    pub fn run(&self) -> Result<Option<MigrationReport>> {
+        let roots =
+            get_legacy_app_dirs().map_err(|e| KeyManagementError::StorageError(e.to_string()))?;
+        if roots.is_empty() {
+            return Ok(None);
+        }
+        self.run_in(&MigrationTargets::resolve()?, &roots)
+    }
+
+    /// Report of the last migration that found a legacy profile
+    pub fn last_report(&self) -> Result<Option<MigrationReport>> {
+        let path = get_config_dir()
+            .map_err(|e| KeyManagementError::StorageError(e.to_string()))?
+            .join(MIGRATION_REPORT_FILENAME);
+        load_report(&path)
+    }
+
+    /// Migrate legacy profiles under `roots` into `targets`
+    pub fn run_in(
+        &self,
+        targets: &MigrationTargets,
+        roots: &[PathBuf],
+    ) -> Result<Option<MigrationReport>> {
+        let profiles: Vec<LegacyProfile> = roots
+            .iter()
+            .filter_map(|root| LegacyProfile::detect(root))
+            .collect();
+        if profiles.is_empty() {
+            debug!("No legacy profile to migrate");
+            return Ok(None);
+        }
+
+        let mut sources = Vec::new();
+        let mut legacy_keys = Vec::new();
+        let mut consumed = Vec::new();
+        for profile in &profiles {
+            let contents = profile
+                .read()
+                .map_err(|e| KeyManagementError::StorageError(e.to_string()))?;
+            info!(
+                path = %profile.root.display(),
+                layout = ?profile.layout,
+                keys = contents.keys.len(),
+                "Found legacy profile"
+            );
+            sources.push(LegacySource {
+                layout: profile.layout,
+                path: profile.root.display().to_string(),
+            });
+            legacy_keys.extend(contents.keys);
+            consumed.extend(contents.consumed);
+        }
+
+        let mut registry = KeyRegistry::load_from(&targets.registry_path)
+            .map_err(|e| KeyManagementError::RegistryLoadFailed(e.to_string()))?;
+        let plan = plan_migration(&registry, &legacy_keys, &targets.keys_dir);
+
+        let mut report = MigrationReport {
+            status: MigrationStatus::Conflict,
+            ran_at: Utc::now(),
+            sources,
+            keys_migrated: Vec::new(),
+            keys_already_present: plan.already_present,
+            key_files_copied: 0,
+            renamed_files: Vec::new(),
+            conflicts: plan.conflicts,
+        };
+
+        if !report.conflicts.is_empty() {
+            warn!(
+                conflicts = report.conflicts.len(),
+                "Legacy profile conflicts with current data, not migrating"
+            );
+            save_report(&targets.report_path, &report)?;
+            return Ok(Some(report));
+        }
+
+        let mut writes = Vec::new();
+        for (key_id, file) in &plan.key_files {
+            let contents = fs::read(file).map_err(|e| {
+                KeyManagementError::StorageError(format!(
+                    "Failed to read legacy key file for '{}': {}",
+                    key_id, e
+                ))
+            })?;
+            let file_name = file.file_name().unwrap_or_default();
+            writes.push(PendingWrite::new(
+                targets.keys_dir.join(file_name),
+                contents,
+            ));
+        }
+        for key in plan.to_add {
+            let label = registry.next_available_label(key.entry.label(), None);
+            let mut entry = key.entry.clone();
+            if label != entry.label() {
+                info!(key_id = %key.key_id, label = %label, "Renamed legacy key label to keep it unique");
+                entry.set_label(label);
+            }
+            registry
+                .register_key(key.key_id.clone(), entry)
+                .map_err(KeyManagementError::InvalidOperation)?;
+            report.keys_migrated.push(key.key_id.clone());
+        }
+        if !report.keys_migrated.is_empty() {
+            let json = serde_json::to_string_pretty(&registry)
+                .map_err(|e| KeyManagementError::RegistrySaveFailed(e.to_string()))?;
+            writes.push(PendingWrite::new(&targets.registry_path, json.into_bytes()));
+        }
+
+        report.key_files_copied = plan.key_files.len();
+        if !writes.is_empty() {
+            let written: Vec<PathBuf> = writes.iter().map(|w| w.path.clone()).collect();
+            targets
+                .journal
+                .apply("migrate_legacy_profile", writes)
+                .map_err(|e| KeyManagementError::StorageError(e.to_string()))?;
+            restrict_permissions(&written);
+        }
+
+        report.renamed_files = rename_consumed(&consumed);
+        report.status = if report.keys_migrated.is_empty() {
+            MigrationStatus::AlreadyPresent
+        } else {
+            MigrationStatus::Migrated
+        };
+
+        info!(
+            keys_migrated = report.keys_migrated.len(),
+            keys_already_present = report.keys_already_present.len(),
+            key_files_copied = report.key_files_copied,
+            "Legacy profile migration completed"
+        );
+
+        save_report(&targets.report_path, &report)?;
+        Ok(Some(report))
+    }
+}
+
+/// What migrating a set of legacy keys would change
+#[derive(Debug, Default)]
+struct MigrationPlan<'a> {
+    to_add: Vec<&'a LegacyKey>,
+    /// Key files to copy, by key ID
+    key_files: Vec<(String, PathBuf)>,
+    already_present: Vec<String>,
+    conflicts: Vec<MigrationConflict>,
+}
+
+fn plan_migration<'a>(
+    registry: &KeyRegistry,
+    legacy_keys: &'a [LegacyKey],
+    keys_dir: &Path,
+) -> MigrationPlan<'a> {
+    let mut plan = MigrationPlan::default();
+    let mut seen: HashMap<&str, &str> = HashMap::new();
+
+    for key in legacy_keys {
+        let public_key = key.entry.public_key();
+
+        // The same key in two legacy profiles is fine; two keys sharing an ID isn't
+        match seen.insert(&key.key_id, public_key) {
+            Some(previous) if previous == public_key => continue,
+            Some(_) => {
+                plan.conflicts.push(MigrationConflict {
+                    key_id: key.key_id.clone(),
+                    reason: "Legacy profiles hold different keys under this ID".to_string(),
+                });
+                continue;
+            }
+            None => {}
+        }
+
+        match registry.get_key(&key.key_id) {
+            Some(existing) if existing.public_key() == public_key => {
+                plan.already_present.push(key.key_id.clone());
+                continue;
+            }
+            Some(_) => {
+                plan.conflicts.push(MigrationConflict {
+                    key_id: key.key_id.clone(),
+                    reason: "The registry already has a different key with this ID".to_string(),
+                });
+                continue;
+            }
+            None => {}
+        }
+        if let Some((existing_id, _)) = registry.find_by_public_key(public_key) {
+            debug!(key_id = %key.key_id, existing_id = %existing_id, "Legacy key already registered under another ID");
+            plan.already_present.push(key.key_id.clone());
+            continue;
+        }
+
+        if let Some(file) = &key.key_file {
+            let target = keys_dir.join(file.file_name().unwrap_or_default());
+            if target.exists() {
+                let same = fs::read(&target).ok() == fs::read(file).ok();
+                if !same {
+                    plan.conflicts.push(MigrationConflict {
+                        key_id: key.key_id.clone(),
+                        reason: format!(
+                            "A different key file named {} already exists",
+                            target.file_name().unwrap_or_default().to_string_lossy()
+                        ),
+                    });
+                    continue;
+                }
+            } else {
+                plan.key_files.push((key.key_id.clone(), file.clone()));
+            }
+        }
+
+        plan.to_add.push(key);
+    }
+
+    plan
+}
+
+/// Keys and registry are owner-only, as when written by `KeyRegistry::save`
+fn restrict_permissions(paths: &[PathBuf]) {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        for path in paths {
+            if let Err(e) = fs::set_permissions(path, fs::Permissions::from_mode(0o600)) {
+                warn!(path = %path.display(), error = %e, "Failed to restrict migrated file permissions");
+            }
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = paths;
+}
+
+/// Rename each consumed legacy file with the `.migrated` suffix
+///
+/// Failures are logged; the data is already migrated either way.
+fn rename_consumed(files: &[PathBuf]) -> Vec<String> {
+    let mut renamed = Vec::new();
+    for file in files {
+        let target = migrated_path(file);
+        match fs::rename(file, &target) {
+            Ok(()) => renamed.push(target.display().to_string()),
+            Err(e) => {
+                warn!(path = %file.display(), error = %e, "Failed to mark legacy file as migrated")
+            }
+        }
+    }
+    renamed
+}
+
+fn save_report(path: &Path, report: &MigrationReport) -> Result<()> {
+    let json = serde_json::to_string_pretty(report)
+        .map_err(|e| KeyManagementError::StorageError(e.to_string()))?;
+    atomic_write_sync(path, json.as_bytes())
+        .map_err(|e| KeyManagementError::StorageError(e.to_string()))
+}
+
+fn load_report(path: &Path) -> Result<Option<MigrationReport>> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    let content =
+        fs::read_to_string(path).map_err(|e| KeyManagementError::StorageError(e.to_string()))?;
+    serde_json::from_str(&content)
+        .map(Some)
+        .map_err(|e| KeyManagementError::StorageError(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::key_management::shared::KeyEntry;
+    use crate::services::key_management::shared::domain::models::key_lifecycle::KeyLifecycleStatus;
+    use crate::services::key_management::shared::domain::models::legacy_migration::LegacyLayout;
+    use tempfile::TempDir;
+
+    /// Pre-registry profile: one `.agekey.meta` per passphrase key
+    fn write_metadata_files_profile(root: &Path) {
+        let keys = root.join("keys");
+        fs::create_dir_all(&keys).unwrap();
+        fs::write(keys.join("family-vault.agekey.enc"), b"family key bytes").unwrap();
+        fs::write(
+            keys.join("family-vault.agekey.meta"),
+            r#"{
+                "label": "family-vault",
+                "created_at": "2025-08-08T15:52:09.347467Z",
+                "file_path": "/Users/old/Library/Application Support/com.Barqly.Vault/keys/family-vault.agekey.enc",
+                "public_key": "age1familyvaultkey",
+                "passphrase_hint": "Anniversary + dog's nickname",
+                "last_accessed": null
+            }"#,
+        )
+        .unwrap();
+        fs::write(keys.join("taxes 2024.agekey.enc"), b"tax key bytes").unwrap();
+        fs::write(
+            keys.join("taxes 2024.agekey.meta"),
+            r#"{
+                "label": "taxes 2024",
+                "created_at": "2025-07-15T09:00:00Z",
+                "file_path": "/Users/old/Library/Application Support/com.Barqly.Vault/keys/taxes 2024.agekey.enc",
+                "public_key": "age1taxeskey",
+                "last_accessed": "2025-08-01T12:00:00Z"
+            }"#,
+        )
+        .unwrap();
+    }
+
+    /// First registry builds: `key-registry.json`, schema v1, no model names
+    fn write_early_registry_profile(root: &Path) {
+        let keys = root.join("keys");
+        fs::create_dir_all(&keys).unwrap();
+        fs::write(keys.join("Work-Key.agekey.enc"), b"work key bytes").unwrap();
+        fs::write(
+            keys.join("key-registry.json"),
+            r#"{
+                "schema": "barqly.vault.registry/1",
+                "keys": {
+                    "Work-Key": {
+                        "type": "passphrase",
+                        "label": "Work Key",
+                        "created_at": "2025-09-01T10:00:00Z",
+                        "last_used": "2025-09-10T10:00:00Z",
+                        "public_key": "age1workkey",
+                        "key_filename": "Work-Key.agekey.enc"
+                    },
+                    "YubiKey-12345678": {
+                        "type": "yubikey",
+                        "label": "YubiKey-12345678",
+                        "created_at": "2025-09-02T10:00:00Z",
+                        "last_used": null,
+                        "serial": "12345678",
+                        "slot": 1,
+                        "piv_slot": 130,
+                        "recipient": "age1yubikey1qexample",
+                        "identity_tag": "AGE-PLUGIN-YUBIKEY-1EXAMPLE",
+                        "firmware_version": "5.4.3",
+                        "recovery_code_hash": "abc123"
+                    }
+                }
+            }"#,
+        )
+        .unwrap();
+    }
+
+    fn targets(temp: &TempDir) -> MigrationTargets {
+        let keys_dir = temp.path().join("current").join("keys");
+        fs::create_dir_all(&keys_dir).unwrap();
+        MigrationTargets {
+            registry_path: keys_dir.join("barqly-vault-key-registry.json"),
+            keys_dir,
+            report_path: temp.path().join("current").join(MIGRATION_REPORT_FILENAME),
+            journal: MutationJournal::at(temp.path().join("journal")),
+        }
+    }
+
+    fn sorted(mut ids: Vec<String>) -> Vec<String> {
+        ids.sort();
+        ids
+    }
+
+    #[test]
+    fn test_migrates_key_metadata_files() {
+        let temp = TempDir::new().unwrap();
+        let legacy = temp.path().join("vault");
+        write_metadata_files_profile(&legacy);
+        let targets = targets(&temp);
+
+        let service = LegacyMigrationService::new();
+        let report = service
+            .run_in(&targets, std::slice::from_ref(&legacy))
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(report.status, MigrationStatus::Migrated);
+        assert_eq!(report.sources[0].layout, LegacyLayout::KeyMetadataFiles);
+        assert_eq!(
+            sorted(report.keys_migrated.clone()),
+            vec!["family-vault", "taxes-2024"]
+        );
+        assert_eq!(report.key_files_copied, 2);
+
+        let registry = KeyRegistry::load_from(&targets.registry_path).unwrap();
+        assert_eq!(registry.schema, "barqly.vault.registry/2");
+        match registry.get_key("taxes-2024").unwrap() {
+            KeyEntry::Passphrase {
+                label,
+                public_key,
+                key_filename,
+                lifecycle_status,
+                last_used,
+                ..
+            } => {
+                assert_eq!(label, "taxes 2024");
+                assert_eq!(public_key, "age1taxeskey");
+                assert_eq!(key_filename, "taxes 2024.agekey.enc");
+                assert_eq!(*lifecycle_status, KeyLifecycleStatus::Suspended);
+                assert!(last_used.is_some());
+            }
+            other => panic!("unexpected entry {:?}", other),
+        }
+        assert_eq!(
+            registry.get_key("family-vault").unwrap().lifecycle_status(),
+            KeyLifecycleStatus::PreActivation
+        );
+        assert_eq!(
+            fs::read(targets.keys_dir.join("family-vault.agekey.enc")).unwrap(),
+            b"family key bytes"
+        );
+
+        // Legacy files are kept, renamed
+        let legacy_keys = legacy.join("keys");
+        assert!(!legacy_keys.join("family-vault.agekey.meta").exists());
+        assert!(
+            legacy_keys
+                .join("family-vault.agekey.meta.migrated")
+                .exists()
+        );
+        assert!(
+            legacy_keys
+                .join("family-vault.agekey.enc.migrated")
+                .exists()
+        );
+
+        assert_eq!(load_report(&targets.report_path).unwrap(), Some(report));
+    }
+
+    #[test]
+    fn test_migrates_early_registry() {
+        let temp = TempDir::new().unwrap();
+        let legacy = temp.path().join("barqly").join("vault").join("data");
+        write_early_registry_profile(&legacy);
+        let targets = targets(&temp);
+
+        let report = LegacyMigrationService::new()
+            .run_in(&targets, std::slice::from_ref(&legacy))
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(report.status, MigrationStatus::Migrated);
+        assert_eq!(report.sources[0].layout, LegacyLayout::EarlyRegistry);
+        assert_eq!(
+            sorted(report.keys_migrated.clone()),
+            vec!["Work-Key", "YubiKey-12345678"]
+        );
+        assert_eq!(report.key_files_copied, 1);
+
+        let registry = KeyRegistry::load_from(&targets.registry_path).unwrap();
+        assert_eq!(registry.keys.len(), 2);
+        assert_eq!(registry.get_key("Work-Key").unwrap().label(), "Work Key");
+        match registry.get_key("YubiKey-12345678").unwrap() {
+            KeyEntry::Yubikey {
+                serial,
+                piv_slot,
+                recipient,
+                model,
+                firmware_version,
+                lifecycle_status,
+                ..
+            } => {
+                assert_eq!(serial, "12345678");
+                assert_eq!(*piv_slot, 130);
+                assert_eq!(recipient, "age1yubikey1qexample");
+                assert_eq!(model, "YubiKey");
+                assert_eq!(firmware_version.as_deref(), Some("5.4.3"));
+                assert_eq!(*lifecycle_status, KeyLifecycleStatus::PreActivation);
+            }
+            other => panic!("unexpected entry {:?}", other),
+        }
+        assert!(
+            legacy
+                .join("keys")
+                .join("key-registry.json.migrated")
+                .exists()
+        );
+    }
+
+    #[test]
+    fn test_second_run_is_a_noop() {
+        let temp = TempDir::new().unwrap();
+        let old_linux = temp.path().join("vault");
+        let old_windows = temp.path().join("barqly").join("vault").join("data");
+        write_metadata_files_profile(&old_linux);
+        write_early_registry_profile(&old_windows);
+        let targets = targets(&temp);
+        let roots = vec![old_linux, old_windows];
+
+        let service = LegacyMigrationService::new();
+        let first = service.run_in(&targets, &roots).unwrap().unwrap();
+        assert_eq!(first.keys_migrated.len(), 4);
+        let registry_after_first = fs::read(&targets.registry_path).unwrap();
+
+        assert!(service.run_in(&targets, &roots).unwrap().is_none());
+        assert_eq!(
+            fs::read(&targets.registry_path).unwrap(),
+            registry_after_first
+        );
+        assert_eq!(load_report(&targets.report_path).unwrap(), Some(first));
+    }
+
+    #[test]
+    fn test_interrupted_run_finishes_renames() {
+        let temp = TempDir::new().unwrap();
+        let legacy = temp.path().join("vault");
+        write_metadata_files_profile(&legacy);
+        let targets = targets(&temp);
+
+        // Keys written, but the legacy files were never renamed
+        let service = LegacyMigrationService::new();
+        service
+            .run_in(&targets, std::slice::from_ref(&legacy))
+            .unwrap();
+        for entry in fs::read_dir(legacy.join("keys")).unwrap() {
+            let path = entry.unwrap().path();
+            let name = path.to_string_lossy().to_string();
+            fs::rename(&path, name.trim_end_matches(".migrated")).unwrap();
+        }
+
+        let report = service
+            .run_in(&targets, std::slice::from_ref(&legacy))
+            .unwrap()
+            .unwrap();
+        assert_eq!(report.status, MigrationStatus::AlreadyPresent);
+        assert!(report.keys_migrated.is_empty());
+        assert_eq!(report.keys_already_present.len(), 2);
+        assert!(LegacyProfile::detect(&legacy).is_none());
+    }
+
+    #[test]
+    fn test_conflicting_registry_is_not_overwritten() {
+        let temp = TempDir::new().unwrap();
+        let legacy = temp.path().join("vault");
+        write_early_registry_profile(&legacy);
+        let targets = targets(&temp);
+
+        let mut registry = KeyRegistry::new();
+        registry
+            .register_key(
+                "Work-Key".to_string(),
+                KeyEntry::Recipient {
+                    label: "Someone else".to_string(),
+                    created_at: Utc::now(),
+                    last_used: None,
+                    public_key: "age1someoneelse".to_string(),
+                    lifecycle_status: KeyLifecycleStatus::Active,
+                    status_history: vec![],
+                    vault_associations: vec![],
+                    deactivated_at: None,
+                    previous_lifecycle_status: None,
+                },
+            )
+            .unwrap();
+        let json = serde_json::to_string_pretty(&registry).unwrap();
+        fs::write(&targets.registry_path, &json).unwrap();
+
+        let report = LegacyMigrationService::new()
+            .run_in(&targets, std::slice::from_ref(&legacy))
+            .unwrap()
+            .unwrap();
+
+        assert!(report.has_conflicts());
+        assert_eq!(report.conflicts[0].key_id, "Work-Key");
+        assert!(report.keys_migrated.is_empty());
+        assert!(report.renamed_files.is_empty());
+        assert_eq!(fs::read_to_string(&targets.registry_path).unwrap(), json);
+        assert!(!targets.keys_dir.join("Work-Key.agekey.enc").exists());
+        assert!(legacy.join("keys").join("key-registry.json").exists());
+        assert_eq!(load_report(&targets.report_path).unwrap(), Some(report));
+    }
+}
@@ -2,10 +2,18 @@
 //!
 //! Business logic services for shared key management operations.
 
+pub mod contact_service;
 pub mod import_service;
+pub mod key_removal_planner_service;
+pub mod legacy_migration_service;
 pub mod registry_service;
 pub mod unified_key_list_service;
+pub mod yubikey_replacement_service;
 
+pub use contact_service::ContactService;
 pub use import_service::{ImportError, KeyImportService, ValidationStatus};
+pub use key_removal_planner_service::KeyRemovalPlannerService;
+pub use legacy_migration_service::{LegacyMigrationService, MigrationTargets};
 pub use registry_service::{KeyManagementError, KeyRegistryService};
 pub use unified_key_list_service::UnifiedKeyListService;
+pub use yubikey_replacement_service::{ReplacementBackend, UnlockKey, YubiKeyReplacementService};
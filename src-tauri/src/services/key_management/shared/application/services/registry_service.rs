@@ -10,18 +10,26 @@
 //! - Comprehensive error handling and logging
 
 use crate::prelude::*;
+use crate::services::key_management::passphrase::domain::LEGACY_PASSPHRASE_POLICY;
+use crate::services::key_management::shared::domain::models::contact::ContactError;
 use crate::services::key_management::shared::domain::models::key_lifecycle::{
     KeyLifecycleStatus, StatusHistoryEntry,
 };
+use crate::services::key_management::shared::domain::models::key_location::KeyFileLocation;
 use crate::services::key_management::shared::infrastructure::{
-    KeyEntry, KeyInfo, KeyRegistry, list_keys as list_key_files,
+    KeyEntry, KeyInfo, KeyRegistry, LabelConflict, LabelRename, SystemVolumes, VolumeSource,
+    list_keys as list_key_files,
 };
 use crate::services::shared;
-use crate::services::shared::infrastructure::get_keys_dir;
+use crate::services::shared::infrastructure::{
+    ChangeEvent, MutationJournal, apply_and_publish, get_keys_dir, publish,
+};
 use crate::services::vault;
 use crate::services::vault::infrastructure::persistence::metadata::{
     RecipientInfo, RecipientType, VaultMetadata,
 };
+use crate::services::vault::infrastructure::persistence::snapshot_before;
+use std::path::Path;
 
 /// Error types for key registry operations
 #[derive(Debug, thiserror::Error)]
@@ -44,6 +52,12 @@ pub enum KeyManagementError {
     #[error("Key already exists: {0}")]
     KeyAlreadyExists(String),
 
+    #[error("Label '{label}' is already used by key '{existing_key_id}'")]
+    LabelConflict {
+        label: String,
+        existing_key_id: String,
+    },
+
     #[error("Invalid operation: {0}")]
     InvalidOperation(String),
 
@@ -52,10 +66,30 @@ pub enum KeyManagementError {
 
     #[error("Configuration error: {0}")]
     ConfigurationError(String),
+
+    #[error(
+        "Removing the key leaves {0} archive(s) unreadable; confirm with the current removal plan"
+    )]
+    RemovalUnconfirmed(usize),
+
+    #[error("YubiKey {0} is connected but not registered")]
+    ReplacementNotRegistered(String),
+
+    #[error(transparent)]
+    Contact(#[from] ContactError),
 }
 
 pub type Result<T> = std::result::Result<T, KeyManagementError>;
 
+impl From<LabelConflict> for KeyManagementError {
+    fn from(conflict: LabelConflict) -> Self {
+        KeyManagementError::LabelConflict {
+            label: conflict.label,
+            existing_key_id: conflict.existing_key_id,
+        }
+    }
+}
+
 /// Merge strategy for handling duplicate public keys
 #[derive(Debug, Clone, Copy)]
 pub enum MergeStrategy {
@@ -116,6 +150,9 @@ pub fn register_key(&self, key_id: String, entry: KeyEntry) -> Result<()> {
 
         let mut registry = self.load_registry()?;
 
+        registry.check_label_available(entry.label(), None)?;
+        let label = entry.label().to_string();
+
         registry.register_key(key_id.clone(), entry).map_err(|e| {
             error!(key_id = %key_id, error = %e, "Failed to register key");
             KeyManagementError::KeyAlreadyExists(key_id.clone())
@@ -125,6 +162,10 @@ pub fn register_key(&self, key_id: String, entry: KeyEntry) -> Result<()> {
             error!(error = %e, "Failed to save registry after key registration");
             KeyManagementError::RegistrySaveFailed(e.to_string())
         })?;
+        publish(ChangeEvent::KeyAdded {
+            key_id: key_id.clone(),
+            label,
+        });
 
         info!(key_id = %key_id, "Key registered successfully");
         Ok(())
@@ -137,6 +178,9 @@ pub fn update_key(&self, key_id: &str, entry: KeyEntry) -> Result<()> {
 
         let mut registry = self.load_registry()?;
 
+        registry.check_label_change(key_id, entry.label())?;
+        let renamed = Self::renamed_label(&registry, key_id, &entry);
+
         registry.update_key(key_id, entry).map_err(|e| {
             error!(key_id = %key_id, error = %e, "Failed to update key");
             KeyManagementError::KeyNotFound(key_id.to_string())
@@ -146,11 +190,169 @@ pub fn update_key(&self, key_id: &str, entry: KeyEntry) -> Result<()> {
             error!(error = %e, "Failed to save registry after key update");
             KeyManagementError::RegistrySaveFailed(e.to_string())
         })?;
+        if let Some(label) = renamed {
+            publish(ChangeEvent::KeyRenamed {
+                key_id: key_id.to_string(),
+                label,
+            });
+        }
 
         info!(key_id = %key_id, "Key updated successfully");
         Ok(())
     }
 
+    /// Update a key entry and a vault manifest as one journaled mutation
+    pub fn update_key_with_manifest(
+        &self,
+        key_id: &str,
+        entry: KeyEntry,
+        metadata: &VaultMetadata,
+    ) -> Result<()> {
+        info!(key_id = %key_id, vault_id = %metadata.vault_id(), "Updating key and vault manifest");
+
+        let mut registry = self.load_registry()?;
+
+        registry.check_label_change(key_id, entry.label())?;
+        let renamed = Self::renamed_label(&registry, key_id, &entry);
+
+        registry.update_key(key_id, entry).map_err(|e| {
+            error!(key_id = %key_id, error = %e, "Failed to update key");
+            KeyManagementError::KeyNotFound(key_id.to_string())
+        })?;
+
+        let writes = vec![
+            registry
+                .to_pending_write()
+                .map_err(|e| KeyManagementError::RegistrySaveFailed(e.to_string()))?,
+            vault::vault_pending_write(metadata)
+                .map_err(|e| KeyManagementError::StorageError(e.to_string()))?,
+        ];
+        snapshot_before("update_key_label");
+        let events = renamed
+            .map(|label| ChangeEvent::KeyRenamed {
+                key_id: key_id.to_string(),
+                label,
+            })
+            .into_iter()
+            .collect();
+        MutationJournal::open()
+            .and_then(|journal| apply_and_publish(&journal, "update_key_label", writes, events))
+            .map_err(|e| {
+                error!(key_id = %key_id, error = %e, "Failed to save key and vault manifest");
+                KeyManagementError::StorageError(e.to_string())
+            })?;
+
+        Ok(())
+    }
+
+    /// Make every key label unique, keeping vault manifests in step
+    ///
+    /// Duplicates are renamed by `KeyRegistry::normalize_labels`. Renamed keys
+    /// attached to vaults get their recipient label updated in each manifest
+    /// (matched by public key), and registry and manifests are written as one
+    /// journaled mutation.
+    #[instrument(skip(self))]
+    pub async fn normalize_labels(&self) -> Result<Vec<LabelRename>> {
+        let mut registry = self.load_registry()?;
+        let renames = registry.normalize_labels();
+
+        if renames.is_empty() {
+            debug!("Key labels already unique");
+            return Ok(renames);
+        }
+
+        let mut vault_ids: Vec<String> = renames
+            .iter()
+            .filter_map(|rename| registry.get_key(&rename.key_id))
+            .flat_map(|entry| entry.vault_associations().to_vec())
+            .collect();
+        vault_ids.sort();
+        vault_ids.dedup();
+
+        let mut manifests: Vec<VaultMetadata> = Vec::new();
+        for vault_id in vault_ids {
+            match vault::load_vault(&vault_id).await {
+                Ok(mut metadata) => {
+                    if Self::relabel_recipients(&registry, &renames, &mut metadata) {
+                        manifests.push(metadata);
+                    }
+                }
+                Err(e) => {
+                    warn!(vault_id = %vault_id, error = %e, "Failed to load vault while renaming key labels");
+                }
+            }
+        }
+
+        let mut writes = vec![
+            registry
+                .to_pending_write()
+                .map_err(|e| KeyManagementError::RegistrySaveFailed(e.to_string()))?,
+        ];
+        for metadata in &manifests {
+            writes.push(
+                vault::vault_pending_write(metadata)
+                    .map_err(|e| KeyManagementError::StorageError(e.to_string()))?,
+            );
+        }
+        snapshot_before("normalize_key_labels");
+        let events = renames
+            .iter()
+            .map(|rename| ChangeEvent::KeyRenamed {
+                key_id: rename.key_id.clone(),
+                label: rename.new_label.clone(),
+            })
+            .collect();
+        MutationJournal::open()
+            .and_then(|journal| apply_and_publish(&journal, "normalize_key_labels", writes, events))
+            .map_err(|e| {
+                error!(error = %e, "Failed to save normalized key labels");
+                KeyManagementError::StorageError(e.to_string())
+            })?;
+
+        info!(
+            renamed = renames.len(),
+            vaults_updated = manifests.len(),
+            "Normalized duplicate key labels"
+        );
+        Ok(renames)
+    }
+
+    /// The new label if `entry` renames the registered key
+    fn renamed_label(registry: &KeyRegistry, key_id: &str, entry: &KeyEntry) -> Option<String> {
+        registry
+            .get_key(key_id)
+            .filter(|current| current.label() != entry.label())
+            .map(|_| entry.label().to_string())
+    }
+
+    /// Carry label renames into a vault manifest's recipients
+    ///
+    /// Recipients are matched by public key and old label. Returns whether
+    /// the manifest changed.
+    pub fn relabel_recipients(
+        registry: &KeyRegistry,
+        renames: &[LabelRename],
+        manifest: &mut VaultMetadata,
+    ) -> bool {
+        let mut changed = false;
+
+        for rename in renames {
+            let Some(entry) = registry.get_key(&rename.key_id) else {
+                continue;
+            };
+
+            for recipient in manifest.recipients_mut().iter_mut() {
+                if recipient.public_key == entry.public_key() && recipient.label == rename.old_label
+                {
+                    recipient.label = rename.new_label.clone();
+                    changed = true;
+                }
+            }
+        }
+
+        changed
+    }
+
     /// Remove a key from the registry
     #[instrument(skip(self))]
     pub fn remove_key(&self, key_id: &str) -> Result<KeyEntry> {
@@ -167,6 +369,9 @@ pub fn remove_key(&self, key_id: &str) -> Result<KeyEntry> {
             error!(error = %e, "Failed to save registry after key removal");
             KeyManagementError::RegistrySaveFailed(e.to_string())
         })?;
+        publish(ChangeEvent::KeyRemoved {
+            key_id: key_id.to_string(),
+        });
 
         info!(key_id = %key_id, "Key removed successfully");
         Ok(removed_entry)
@@ -199,6 +404,61 @@ pub fn mark_key_used(&self, key_id: &str) -> Result<()> {
         Ok(())
     }
 
+    /// Point a passphrase key at its key file's new location
+    ///
+    /// The file must be a passphrase-protected age file; whether it's the
+    /// right one only shows when the passphrase is next used. The mounted
+    /// volume holding it is recorded so the key is found again when the
+    /// drive mounts at a different path.
+    #[instrument(skip(self))]
+    pub fn relink_key_file(&self, key_id: &str, new_path: &Path) -> Result<KeyFileLocation> {
+        info!(key_id = %key_id, "Relinking passphrase key file");
+
+        let mut registry = self.load_registry()?;
+
+        match registry.get_key(key_id) {
+            Some(KeyEntry::Passphrase { .. }) => {}
+            Some(_) => {
+                return Err(KeyManagementError::InvalidOperation(format!(
+                    "Key '{}' is not a passphrase key",
+                    key_id
+                )));
+            }
+            None => return Err(KeyManagementError::KeyNotFound(key_id.to_string())),
+        }
+
+        let key_path = std::fs::canonicalize(new_path).map_err(|e| {
+            KeyManagementError::StorageError(format!("{}: {}", new_path.display(), e))
+        })?;
+        let file = std::fs::File::open(&key_path)
+            .map_err(|e| KeyManagementError::StorageError(e.to_string()))?;
+        let is_passphrase_key = age::Decryptor::new(std::io::BufReader::new(file))
+            .map(|decryptor| decryptor.is_scrypt())
+            .unwrap_or(false);
+        if !is_passphrase_key {
+            return Err(KeyManagementError::InvalidOperation(format!(
+                "{} is not a passphrase-protected key file",
+                key_path.display()
+            )));
+        }
+
+        let location = KeyFileLocation::for_path(&key_path, &SystemVolumes.mounted_volumes());
+        registry
+            .relink_key_file(key_id, location.clone())
+            .map_err(KeyManagementError::InvalidOperation)?;
+
+        registry
+            .save()
+            .map_err(|e| KeyManagementError::RegistrySaveFailed(e.to_string()))?;
+
+        info!(
+            key_id = %key_id,
+            volume_label = ?location.volume_label(),
+            "Passphrase key file relinked"
+        );
+        Ok(location)
+    }
+
     /// Get all keys that match YubiKey serial
     #[instrument(skip(self))]
     pub fn find_yubikey_by_serial(&self, serial: &str) -> Result<Option<(String, KeyEntry)>> {
@@ -240,6 +500,9 @@ pub async fn detach_key_from_vault(&self, key_id: &str, vault_id: &str) -> Resul
             crate::services::key_management::shared::KeyEntry::Recipient { label, .. } => {
                 label.clone()
             }
+            crate::services::key_management::shared::KeyEntry::YubikeyPending { label, .. } => {
+                label.clone()
+            }
         };
 
         // Remove recipient by label (idempotent)
@@ -254,12 +517,6 @@ pub async fn detach_key_from_vault(&self, key_id: &str, vault_id: &str) -> Resul
             return Ok(()); // Already not attached - success (no-op)
         }
 
-        // Save updated vault metadata
-        vault::save_vault(&metadata).await.map_err(|e| {
-            error!(vault_id = %vault_id, error = %e, "Failed to save vault after detaching key");
-            KeyManagementError::StorageError(e.to_string())
-        })?;
-
         // CRITICAL: Update global key registry (mirror of attach logic)
         // This was missing and caused registry-manifest desynchronization
 
@@ -316,11 +573,27 @@ pub async fn detach_key_from_vault(&self, key_id: &str, vault_id: &str) -> Resul
             }
         } // key_entry mutable borrow ends here
 
-        // Step 5: Save updated registry (borrow ended, safe to save)
-        registry.save().map_err(|e| {
-            error!(error = %e, "Failed to save registry after key detachment");
-            KeyManagementError::RegistrySaveFailed(e.to_string())
-        })?;
+        // Step 5: Save manifest and registry together through the journal
+        let writes = vec![
+            vault::vault_pending_write(&metadata)
+                .map_err(|e| KeyManagementError::StorageError(e.to_string()))?,
+            registry
+                .to_pending_write()
+                .map_err(|e| KeyManagementError::RegistrySaveFailed(e.to_string()))?,
+        ];
+        snapshot_before("remove_key_from_vault");
+        let events = vec![ChangeEvent::KeyDetached {
+            key_id: key_id.to_string(),
+            vault_id: vault_id.to_string(),
+        }];
+        MutationJournal::open()
+            .and_then(|journal| {
+                apply_and_publish(&journal, "remove_key_from_vault", writes, events)
+            })
+            .map_err(|e| {
+                error!(vault_id = %vault_id, error = %e, "Failed to save vault and registry after key detachment");
+                KeyManagementError::StorageError(e.to_string())
+            })?;
 
         info!(
             key_id = %key_id,
@@ -348,6 +621,9 @@ pub async fn is_key_used_by_vaults(&self, key_id: &str) -> Result<Vec<String>> {
             crate::services::key_management::shared::KeyEntry::Recipient { label, .. } => {
                 label.clone()
             }
+            crate::services::key_management::shared::KeyEntry::YubikeyPending { label, .. } => {
+                label.clone()
+            }
         };
 
         // List all vaults
@@ -452,6 +728,8 @@ pub fn recipient_to_key_entry(recipient: &RecipientInfo) -> KeyEntry {
                 vault_associations: vec![], // Will be populated by higher level
                 deactivated_at: None,
                 previous_lifecycle_status: None,
+                key_location: None,
+                passphrase_policy: LEGACY_PASSPHRASE_POLICY,
             },
             RecipientType::YubiKey {
                 serial,
@@ -481,6 +759,7 @@ pub fn recipient_to_key_entry(recipient: &RecipientInfo) -> KeyEntry {
                 vault_associations: vec![], // Will be populated by higher level
                 deactivated_at: None,
                 previous_lifecycle_status: None,
+                last_reader: None,
             },
             RecipientType::PublicKeyOnly => KeyEntry::Recipient {
                 label: recipient.label.clone(),
@@ -500,6 +779,68 @@ pub fn recipient_to_key_entry(recipient: &RecipientInfo) -> KeyEntry {
         }
     }
 
+    /// Convert a registry KeyEntry to the RecipientInfo a vault manifest lists
+    ///
+    /// `None` for a reserved YubiKey slot, which has no recipient to list.
+    pub fn key_entry_to_recipient(key_id: &str, key_entry: &KeyEntry) -> Option<RecipientInfo> {
+        let recipient = match key_entry {
+            KeyEntry::Passphrase {
+                label,
+                public_key,
+                key_filename,
+                created_at,
+                ..
+            } => RecipientInfo {
+                key_id: key_id.to_string(),
+                recipient_type: RecipientType::Passphrase {
+                    key_filename: key_filename.clone(),
+                },
+                public_key: public_key.clone(),
+                label: label.clone(),
+                created_at: *created_at,
+            },
+            KeyEntry::Yubikey {
+                label,
+                recipient,
+                serial,
+                slot,
+                piv_slot,
+                identity_tag,
+                model,
+                firmware_version,
+                created_at,
+                ..
+            } => RecipientInfo {
+                key_id: key_id.to_string(),
+                recipient_type: RecipientType::YubiKey {
+                    serial: serial.clone(),
+                    slot: *slot,
+                    piv_slot: *piv_slot,
+                    model: model.clone(),
+                    identity_tag: identity_tag.clone(),
+                    firmware_version: firmware_version.clone(),
+                },
+                public_key: recipient.clone(),
+                label: label.clone(),
+                created_at: *created_at,
+            },
+            KeyEntry::Recipient {
+                label,
+                public_key,
+                created_at,
+                ..
+            } => RecipientInfo {
+                key_id: key_id.to_string(),
+                recipient_type: RecipientType::PublicKeyOnly,
+                public_key: public_key.clone(),
+                label: label.clone(),
+                created_at: *created_at,
+            },
+            KeyEntry::YubikeyPending { .. } => return None,
+        };
+        Some(recipient)
+    }
+
     /// Find key by public key (returns key_id if exists)
     #[instrument(skip(self))]
     pub fn find_by_public_key(&self, public_key: &str) -> Result<Option<String>> {
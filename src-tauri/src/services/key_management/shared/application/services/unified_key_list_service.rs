@@ -7,11 +7,14 @@
 use crate::services::key_management::passphrase::domain::models::passphrase_key_info::PassphraseKeyInfo;
 use crate::services::key_management::shared::KeyEntry;
 use crate::services::key_management::shared::application::services::KeyRegistryService;
-use crate::services::key_management::shared::domain::models::KeyType;
 use crate::services::key_management::shared::domain::models::key_lifecycle::KeyLifecycleStatus;
 use crate::services::key_management::shared::domain::models::key_reference::{
     GlobalKey, KeyListFilter, YubiKeyInfo,
 };
+use crate::services::key_management::shared::domain::models::{
+    KeyFileLocation, KeyType, MountedVolume,
+};
+use crate::services::key_management::shared::infrastructure::{SystemVolumes, VolumeSource};
 use crate::services::key_management::yubikey::YubiKeyManager;
 use crate::services::key_management::yubikey::domain::models::{
     available_yubikey::AvailableYubiKey,
@@ -19,8 +22,24 @@
     yubikey_state_info::YubiKeyStateInfo,
 };
 use crate::services::vault::VaultManager;
+use std::cell::OnceCell;
 use std::collections::HashSet;
 
+/// Whether a passphrase key's file can be found right now
+///
+/// Keys in the keys directory are always available; only relinked keys can
+/// go missing. Volumes are scanned at most once per listing.
+fn key_file_available(
+    key_location: Option<&KeyFileLocation>,
+    volumes: &OnceCell<Vec<MountedVolume>>,
+) -> bool {
+    key_location.is_none_or(|location| {
+        location
+            .resolve(volumes.get_or_init(|| SystemVolumes.mounted_volumes()))
+            .is_some()
+    })
+}
+
 // Conversion functions to transform Layer 2 types to unified types
 
 /// Convert PassphraseKeyInfo to unified GlobalKey
@@ -123,6 +142,25 @@ fn convert_available_yubikey_to_unified(available_key: AvailableYubiKey) -> Glob
     }
 }
 
+/// Convert a reserved YubiKey slot to unified GlobalKey
+///
+/// Never available: there is no hardware behind it yet.
+fn convert_pending_yubikey_to_unified(key_id: &str, entry: &KeyEntry) -> GlobalKey {
+    GlobalKey {
+        id: key_id.to_string(),
+        label: entry.label().to_string(),
+        key_type: KeyType::YubiKeyPending,
+        recipient: String::new(),
+        is_available: false,
+        vault_associations: entry.vault_associations().to_vec(),
+        lifecycle_status: entry.lifecycle_status(),
+        created_at: entry.created_at(),
+        last_used: None,
+        yubikey_info: None,
+        deactivated_at: entry.deactivated_at(),
+    }
+}
+
 /// Service for unified key listing across all key types
 #[derive(Debug)]
 pub struct UnifiedKeyListService {
@@ -174,6 +212,8 @@ async fn list_all_keys(&self) -> Result<Vec<GlobalKey>, Box<dyn std::error::Erro
             }
         };
 
+        let volumes = OnceCell::new();
+
         // Iterate through ALL registry entries (passphrase + yubikey)
         for (key_id, entry) in registry.keys {
             match entry {
@@ -185,6 +225,7 @@ async fn list_all_keys(&self) -> Result<Vec<GlobalKey>, Box<dyn std::error::Erro
                     vault_associations,
                     lifecycle_status,
                     deactivated_at,
+                    key_location,
                     ..
                 } => {
                     // Build GlobalKey directly to include deactivated_at
@@ -193,7 +234,8 @@ async fn list_all_keys(&self) -> Result<Vec<GlobalKey>, Box<dyn std::error::Erro
                         label,
                         key_type: KeyType::Passphrase { key_id },
                         recipient: public_key,
-                        is_available: true, // Passphrase keys are always available (file-based)
+                        // Relinked keys are unavailable while their drive is out
+                        is_available: key_file_available(key_location.as_ref(), &volumes),
                         vault_associations,
                         lifecycle_status,
                         created_at,
@@ -294,6 +336,9 @@ async fn list_all_keys(&self) -> Result<Vec<GlobalKey>, Box<dyn std::error::Erro
 
                     all_keys.push(key_info);
                 }
+                KeyEntry::YubikeyPending { .. } => {
+                    all_keys.push(convert_pending_yubikey_to_unified(&key_id, &entry));
+                }
             }
         }
 
@@ -322,6 +367,7 @@ async fn list_vault_keys(
         };
 
         // Get passphrase keys for vault
+        let volumes = OnceCell::new();
         match self.registry_service.load_registry() {
             Ok(registry) => {
                 for key_id in &vault.get_key_ids() {
@@ -331,6 +377,7 @@ async fn list_vault_keys(
                         last_used,
                         public_key,
                         vault_associations,
+                        key_location,
                         ..
                     }) = registry.get_key(key_id)
                     {
@@ -340,7 +387,7 @@ async fn list_vault_keys(
                             public_key: public_key.clone(),
                             created_at: *created_at,
                             last_used: *last_used,
-                            is_available: true,
+                            is_available: key_file_available(key_location.as_ref(), &volumes),
                         };
                         unified_keys.push(convert_passphrase_to_unified(
                             passphrase_info,
@@ -348,6 +395,11 @@ async fn list_vault_keys(
                         ));
                     }
                 }
+
+                // Reserved YubiKey slots only join the manifest once fulfilled
+                for (key_id, entry) in registry.pending_yubikeys(&vault_id) {
+                    unified_keys.push(convert_pending_yubikey_to_unified(key_id, entry));
+                }
             }
             Err(e) => {
                 warn!(vault_id = %vault_id, error = ?e, "Failed to load registry");
@@ -419,6 +471,7 @@ async fn list_available_for_vault(
                 let vault_key_ids: HashSet<String> = vault.get_key_ids().into_iter().collect();
 
                 // Get available passphrase keys (not in vault)
+                let volumes = OnceCell::new();
                 match self.registry_service.load_registry() {
                     Ok(registry) => {
                         for (key_id, entry) in registry.keys.iter() {
@@ -428,6 +481,7 @@ async fn list_available_for_vault(
                                 last_used,
                                 public_key,
                                 vault_associations,
+                                key_location,
                                 ..
                             } = entry
                                 && !vault_key_ids.contains(key_id)
@@ -438,7 +492,10 @@ async fn list_available_for_vault(
                                     public_key: public_key.clone(),
                                     created_at: *created_at,
                                     last_used: *last_used,
-                                    is_available: true,
+                                    is_available: key_file_available(
+                                        key_location.as_ref(),
+                                        &volumes,
+                                    ),
                                 };
                                 available_keys.push(convert_passphrase_to_unified(
                                     passphrase_info,
@@ -543,7 +600,9 @@ async fn list_available_for_vault(
     async fn list_connected_keys(&self) -> Result<Vec<GlobalKey>, Box<dyn std::error::Error>> {
         let mut connected_keys = Vec::new();
 
-        // All passphrase keys are always "connected" (available on disk)
+        // Passphrase keys are listed even when their drive is out, marked
+        // unavailable, so the decrypt UI can ask for the drive
+        let volumes = OnceCell::new();
         match self.registry_service.load_registry() {
             Ok(registry) => {
                 for (key_id, entry) in registry.keys {
@@ -553,6 +612,7 @@ async fn list_connected_keys(&self) -> Result<Vec<GlobalKey>, Box<dyn std::error
                         last_used,
                         public_key,
                         vault_associations,
+                        key_location,
                         ..
                     } = entry
                     {
@@ -562,7 +622,7 @@ async fn list_connected_keys(&self) -> Result<Vec<GlobalKey>, Box<dyn std::error
                             public_key,
                             created_at,
                             last_used,
-                            is_available: true,
+                            is_available: key_file_available(key_location.as_ref(), &volumes),
                         };
                         connected_keys.push(convert_passphrase_to_unified(
                             passphrase_info,
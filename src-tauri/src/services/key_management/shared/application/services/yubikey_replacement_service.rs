@@ -0,0 +1,771 @@
+//! YubiKey Replacement Service
+//!
+//! Walks through what losing a registered YubiKey requires: find the
+//! connected replacement, re-encrypt each vault's latest archive without the
+//! lost key, move the vault's manifest to the replacement, and revoke the
+//! lost entry.
+//!
+//! A vault is only moved once its archive was re-encrypted. When that fails
+//! the vault keeps listing the lost key and is reported as still exposed;
+//! running the replacement again picks it up.
+
+use super::registry_service::{KeyManagementError, KeyRegistryService, Result};
+use crate::prelude::*;
+use crate::services::crypto::application::services::PassphraseDecryptionService;
+use crate::services::crypto::infrastructure::{
+    PublicKey, armor_archive, decrypt_data_yubikey_cli, encrypt_data_multi_recipient,
+    read_age_archive, read_archive_preamble,
+};
+use crate::services::key_management::shared::domain::models::key_replacement::{
+    OLD_ARCHIVES_NOTICE, VaultReplacementOutcome, VaultReplacementStatus, VaultSelection,
+    YubiKeyReplacementReport,
+};
+use crate::services::key_management::shared::{KeyEntry, KeyLifecycleStatus, KeyRegistry};
+use crate::services::key_management::yubikey::YubiKeyManager;
+use crate::services::shared::infrastructure::{
+    MutationJournal, ProgressManager, atomic_write_sync, get_vaults_directory,
+};
+use crate::services::vault::VaultManager;
+use crate::services::vault::infrastructure::persistence::metadata::VaultMetadata;
+use crate::services::vault::infrastructure::persistence::{ArchiveIndex, snapshot_before};
+use age::secrecy::{ExposeSecret, SecretString};
+use async_trait::async_trait;
+
+/// Key used to open the archives being re-encrypted
+///
+/// The lost YubiKey can't be used, so another key attached to the vaults
+/// has to unlock them: a passphrase key with its passphrase, or a YubiKey
+/// with its PIN.
+pub struct UnlockKey {
+    pub key_id: String,
+    pub secret: SecretString,
+}
+
+/// Storage and device access used by a replacement
+#[async_trait]
+pub trait ReplacementBackend: Send + Sync {
+    /// Serials of the YubiKeys currently connected
+    async fn connected_serials(&self) -> Result<Vec<String>>;
+
+    fn load_registry(&self) -> Result<KeyRegistry>;
+
+    async fn load_vault(&self, vault_id: &str) -> Result<VaultMetadata>;
+
+    /// Re-encrypt the vault's latest archive to `recipients`, replacing it
+    ///
+    /// Returns the archive name, or `None` when the vault has no archive yet.
+    fn reencrypt_latest_archive(
+        &self,
+        vault: &VaultMetadata,
+        recipients: &[String],
+        unlock: &UnlockKey,
+    ) -> Result<Option<String>>;
+
+    /// Save a vault manifest and the registry together
+    fn save_vault_and_registry(&self, vault: &VaultMetadata, registry: &KeyRegistry) -> Result<()>;
+
+    fn save_registry(&self, registry: &KeyRegistry) -> Result<()>;
+
+    /// Encryptions recorded in the archive index for a vault
+    fn recorded_encryptions(&self, vault_id: &str) -> usize;
+}
+
+/// Service replacing a lost YubiKey across vaults
+pub struct YubiKeyReplacementService {
+    backend: Box<dyn ReplacementBackend>,
+}
+
+impl std::fmt::Debug for YubiKeyReplacementService {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("YubiKeyReplacementService").finish()
+    }
+}
+
+impl YubiKeyReplacementService {
+    pub fn new() -> Self {
+        Self::with_backend(Box::new(LiveReplacementBackend))
+    }
+
+    pub fn with_backend(backend: Box<dyn ReplacementBackend>) -> Self {
+        Self { backend }
+    }
+
+    /// Replace `lost_key_id` in the selected vaults with the connected
+    /// replacement YubiKey
+    ///
+    /// Per-vault failures don't stop the run; they are reported as still
+    /// exposed. The lost key is revoked once, on the first run.
+    #[instrument(skip(self, unlock, progress), fields(unlock_key_id = %unlock.key_id))]
+    pub async fn replace(
+        &self,
+        lost_key_id: &str,
+        selection: &VaultSelection,
+        unlock: &UnlockKey,
+        progress: &mut ProgressManager,
+    ) -> Result<YubiKeyReplacementReport> {
+        let mut registry = self.backend.load_registry()?;
+        let lost = registry
+            .get_key(lost_key_id)
+            .ok_or_else(|| KeyManagementError::KeyNotFound(lost_key_id.to_string()))?
+            .clone();
+        let lost_serial = lost.yubikey_serial().ok_or_else(|| {
+            KeyManagementError::InvalidOperation(format!("Key '{}' is not a YubiKey", lost.label()))
+        })?;
+        if !matches!(
+            lost.lifecycle_status(),
+            KeyLifecycleStatus::Active
+                | KeyLifecycleStatus::Suspended
+                | KeyLifecycleStatus::Revoked
+        ) {
+            return Err(KeyManagementError::InvalidOperation(format!(
+                "Key '{}' is {} and can't be replaced",
+                lost.label(),
+                lost.lifecycle_status()
+            )));
+        }
+        if unlock.key_id == lost_key_id {
+            return Err(KeyManagementError::InvalidOperation(
+                "Choose another key to unlock the archives; the lost YubiKey can't".to_string(),
+            ));
+        }
+
+        let replacement_id = self.find_replacement(&registry, lost_serial).await?;
+        let vault_ids = selected_vaults(&lost, selection)?;
+
+        let total = vault_ids.len();
+        let mut outcomes = Vec::with_capacity(total);
+        for (done, vault_id) in vault_ids.iter().enumerate() {
+            progress.set_progress(
+                done as f32 / total as f32,
+                format!("Re-encrypting vault {} of {}", done + 1, total),
+            );
+            let outcome = self
+                .replace_in_vault(
+                    &mut registry,
+                    vault_id,
+                    lost_key_id,
+                    &replacement_id,
+                    unlock,
+                )
+                .await;
+            outcomes.push(outcome);
+        }
+
+        let lost_key_revoked = self.revoke(&mut registry, lost_key_id, &replacement_id)?;
+        let replacement_label = registry
+            .get_key(&replacement_id)
+            .map(|entry| entry.label().to_string())
+            .unwrap_or_default();
+
+        let exposed = outcomes.iter().filter(|o| o.is_exposed()).count();
+        progress.complete(if exposed == 0 {
+            "Replacement complete".to_string()
+        } else {
+            format!("{exposed} vault(s) could not be re-encrypted")
+        });
+        info!(
+            lost_key_id = %lost_key_id,
+            replacement_key_id = %replacement_id,
+            vaults = outcomes.len(),
+            exposed,
+            "YubiKey replacement finished"
+        );
+
+        Ok(YubiKeyReplacementReport {
+            lost_key_id: lost_key_id.to_string(),
+            replacement_key_id: replacement_id,
+            replacement_label,
+            vaults: outcomes,
+            lost_key_revoked,
+            old_archives_notice: OLD_ARCHIVES_NOTICE.to_string(),
+        })
+    }
+
+    /// The one connected YubiKey, other than the lost one, that is registered
+    async fn find_replacement(&self, registry: &KeyRegistry, lost_serial: &str) -> Result<String> {
+        let serials: Vec<String> = self
+            .backend
+            .connected_serials()
+            .await?
+            .into_iter()
+            .filter(|serial| serial != lost_serial)
+            .collect();
+        if serials.is_empty() {
+            return Err(KeyManagementError::InvalidOperation(
+                "Connect the replacement YubiKey to continue".to_string(),
+            ));
+        }
+
+        let registered: Vec<&String> = serials
+            .iter()
+            .filter_map(|serial| registry.find_yubikey_by_serial(serial))
+            .filter(|(_, entry)| entry.lifecycle_status().can_attach_to_vault())
+            .map(|(key_id, _)| key_id)
+            .collect();
+        match registered.as_slice() {
+            [key_id] => Ok((*key_id).clone()),
+            [] => Err(KeyManagementError::ReplacementNotRegistered(
+                serials[0].clone(),
+            )),
+            _ => Err(KeyManagementError::InvalidOperation(
+                "More than one registered YubiKey is connected; leave only the replacement \
+                 connected"
+                    .to_string(),
+            )),
+        }
+    }
+
+    async fn replace_in_vault(
+        &self,
+        registry: &mut KeyRegistry,
+        vault_id: &str,
+        lost_key_id: &str,
+        replacement_id: &str,
+        unlock: &UnlockKey,
+    ) -> VaultReplacementOutcome {
+        let mut vault_name = vault_id.to_string();
+        let status = match self
+            .move_vault(
+                registry,
+                vault_id,
+                lost_key_id,
+                replacement_id,
+                unlock,
+                &mut vault_name,
+            )
+            .await
+        {
+            Ok(Some(archive_name)) => VaultReplacementStatus::Reencrypted { archive_name },
+            Ok(None) => VaultReplacementStatus::NoArchive,
+            Err(e) => {
+                warn!(vault_id = %vault_id, error = %e, "Vault still exposed to lost YubiKey");
+                VaultReplacementStatus::StillExposed {
+                    error: e.to_string(),
+                }
+            }
+        };
+
+        VaultReplacementOutcome {
+            vault_id: vault_id.to_string(),
+            vault_name,
+            status,
+            earlier_encryptions: self.backend.recorded_encryptions(vault_id),
+        }
+    }
+
+    /// Re-encrypt, then swap the lost key for the replacement in the manifest
+    /// and registry. Nothing is saved if re-encryption fails.
+    async fn move_vault(
+        &self,
+        registry: &mut KeyRegistry,
+        vault_id: &str,
+        lost_key_id: &str,
+        replacement_id: &str,
+        unlock: &UnlockKey,
+        vault_name: &mut String,
+    ) -> Result<Option<String>> {
+        let mut vault = self.backend.load_vault(vault_id).await?;
+        *vault_name = vault.label().to_string();
+
+        let replacement = registry
+            .get_key(replacement_id)
+            .ok_or_else(|| KeyManagementError::KeyNotFound(replacement_id.to_string()))?
+            .clone();
+        vault
+            .recipients_mut()
+            .retain(|recipient| recipient.key_id != lost_key_id);
+        if !vault.get_key_ids().iter().any(|id| id == replacement_id) {
+            let recipient =
+                KeyRegistryService::key_entry_to_recipient(replacement_id, &replacement)
+                    .ok_or_else(|| {
+                        KeyManagementError::InvalidOperation(
+                            "A reserved YubiKey slot can't replace a key".to_string(),
+                        )
+                    })?;
+            vault.add_recipient(recipient);
+        }
+
+        let archive_name =
+            self.backend
+                .reencrypt_latest_archive(&vault, &vault.get_age_recipients(), unlock)?;
+
+        let mut updated = registry.clone();
+        if let Some(lost) = updated.get_key_mut(lost_key_id) {
+            lost.remove_vault_association(vault_id);
+        }
+        if let Some(entry) = updated.get_key_mut(replacement_id) {
+            entry.add_vault_association(vault_id.to_string());
+            if entry.lifecycle_status() != KeyLifecycleStatus::Active {
+                entry
+                    .set_lifecycle_status(
+                        KeyLifecycleStatus::Active,
+                        format!("Replaced a lost YubiKey in vault '{}'", vault_id),
+                        "user".to_string(),
+                    )
+                    .map_err(KeyManagementError::InvalidOperation)?;
+            }
+        }
+        self.backend.save_vault_and_registry(&vault, &updated)?;
+        *registry = updated;
+
+        Ok(archive_name)
+    }
+
+    /// Revoke the lost key; returns whether it is revoked afterwards
+    fn revoke(
+        &self,
+        registry: &mut KeyRegistry,
+        lost_key_id: &str,
+        replacement_id: &str,
+    ) -> Result<bool> {
+        let lost = registry
+            .get_key_mut(lost_key_id)
+            .ok_or_else(|| KeyManagementError::KeyNotFound(lost_key_id.to_string()))?;
+        if lost.lifecycle_status() == KeyLifecycleStatus::Revoked {
+            return Ok(true);
+        }
+
+        lost.revoke(
+            replacement_id,
+            "YubiKey lost and replaced".to_string(),
+            "user".to_string(),
+        )
+        .map_err(KeyManagementError::InvalidOperation)?;
+        self.backend.save_registry(registry)?;
+        Ok(true)
+    }
+}
+
+impl Default for YubiKeyReplacementService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Vault IDs a replacement covers; selected vaults must use the lost key
+fn selected_vaults(lost: &KeyEntry, selection: &VaultSelection) -> Result<Vec<String>> {
+    let associated = lost.vault_associations();
+    match selection {
+        VaultSelection::AllAssociated => Ok(associated.to_vec()),
+        VaultSelection::Selected(vault_ids) => {
+            if let Some(other) = vault_ids.iter().find(|id| !associated.contains(id)) {
+                return Err(KeyManagementError::InvalidOperation(format!(
+                    "Vault '{}' doesn't use the lost YubiKey",
+                    other
+                )));
+            }
+            Ok(vault_ids.clone())
+        }
+    }
+}
+
+/// Backend working on the real registry, vaults and connected devices
+struct LiveReplacementBackend;
+
+#[async_trait]
+impl ReplacementBackend for LiveReplacementBackend {
+    async fn connected_serials(&self) -> Result<Vec<String>> {
+        let manager = YubiKeyManager::new()
+            .await
+            .map_err(|e| KeyManagementError::ConfigurationError(e.to_string()))?;
+        let devices = manager
+            .list_connected_devices()
+            .await
+            .map_err(|e| KeyManagementError::ConfigurationError(e.to_string()))?;
+        Ok(devices
+            .into_iter()
+            .map(|device| device.serial.value().to_string())
+            .collect())
+    }
+
+    fn load_registry(&self) -> Result<KeyRegistry> {
+        KeyRegistryService::new().load_registry()
+    }
+
+    async fn load_vault(&self, vault_id: &str) -> Result<VaultMetadata> {
+        VaultManager::new()
+            .get_vault(vault_id)
+            .await
+            .map_err(|e| KeyManagementError::VaultNotFound(e.to_string()))
+    }
+
+    fn reencrypt_latest_archive(
+        &self,
+        vault: &VaultMetadata,
+        recipients: &[String],
+        unlock: &UnlockKey,
+    ) -> Result<Option<String>> {
+        let storage = |e: &dyn std::fmt::Display| KeyManagementError::StorageError(e.to_string());
+
+        let archive_name = format!("{}.age", vault.vault.sanitized_name);
+        let path = get_vaults_directory()
+            .map_err(|e| storage(&e))?
+            .join(&archive_name);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let index = ArchiveIndex::load().map_err(|e| storage(&e))?;
+        if index
+            .current_entry(&archive_name)
+            .is_some_and(|entry| entry.immutable)
+        {
+            return Err(KeyManagementError::InvalidOperation(format!(
+                "'{}' is marked immutable; clear the flag to re-encrypt it",
+                archive_name
+            )));
+        }
+
+        let unlock_entry = KeyRegistryService::new().get_key(&unlock.key_id)?;
+        // An armored archive keeps its preamble through re-encryption
+        let preamble = read_archive_preamble(&path).map_err(|e| storage(&e))?;
+        let encrypted = read_age_archive(&path).map_err(|e| storage(&e))?;
+        let decrypted = match &unlock_entry {
+            KeyEntry::Passphrase { .. } => PassphraseDecryptionService::new()
+                .decrypt_with_registered_key(
+                    &encrypted,
+                    &unlock.key_id,
+                    &unlock_entry,
+                    unlock.secret.clone(),
+                )
+                .map_err(|e| e.to_string()),
+            KeyEntry::Yubikey { .. } => {
+                decrypt_data_yubikey_cli(&encrypted, &unlock_entry, unlock.secret.expose_secret())
+                    .map_err(|e| e.to_string())
+            }
+            KeyEntry::Recipient { .. } => {
+                return Err(KeyManagementError::InvalidOperation(
+                    "A recipient key can't unlock archives".to_string(),
+                ));
+            }
+            KeyEntry::YubikeyPending { .. } => {
+                return Err(KeyManagementError::InvalidOperation(
+                    "A reserved YubiKey slot can't unlock archives".to_string(),
+                ));
+            }
+        }
+        .map_err(KeyManagementError::InvalidOperation)?;
+
+        let public_keys: Vec<PublicKey> = recipients.iter().cloned().map(PublicKey::from).collect();
+        let mut reencrypted =
+            encrypt_data_multi_recipient(&decrypted, &public_keys).map_err(|e| storage(&e))?;
+        if let Some(preamble) = preamble {
+            reencrypted = armor_archive(&reencrypted, &preamble).map_err(|e| storage(&e))?;
+        }
+        atomic_write_sync(&path, &reencrypted).map_err(|e| storage(&e))?;
+
+        info!(archive = %archive_name, "Re-encrypted archive without the lost YubiKey");
+        Ok(Some(archive_name))
+    }
+
+    fn save_vault_and_registry(&self, vault: &VaultMetadata, registry: &KeyRegistry) -> Result<()> {
+        let writes = vec![
+            crate::services::vault::vault_pending_write(vault)
+                .map_err(|e| KeyManagementError::StorageError(e.to_string()))?,
+            registry
+                .to_pending_write()
+                .map_err(|e| KeyManagementError::RegistrySaveFailed(e.to_string()))?,
+        ];
+        snapshot_before("replace_yubikey");
+        MutationJournal::open()
+            .and_then(|journal| journal.apply("replace_yubikey", writes))
+            .map(|_| ())
+            .map_err(|e| KeyManagementError::StorageError(e.to_string()))
+    }
+
+    fn save_registry(&self, registry: &KeyRegistry) -> Result<()> {
+        registry
+            .save()
+            .map_err(|e| KeyManagementError::RegistrySaveFailed(e.to_string()))
+    }
+
+    fn recorded_encryptions(&self, vault_id: &str) -> usize {
+        ArchiveIndex::load()
+            .map(|index| index.entries(vault_id).len())
+            .unwrap_or(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::key_management::shared::domain::models::key_lifecycle::StatusHistoryEntry;
+    use crate::services::shared::infrastructure::DeviceInfo;
+    use crate::services::vault::infrastructure::persistence::metadata::RecipientInfo;
+    use chrono::Utc;
+    use std::collections::{HashMap, HashSet};
+    use std::sync::Mutex;
+
+    const LOST_SERIAL: &str = "11111111";
+    const NEW_SERIAL: &str = "22222222";
+
+    fn yubikey(label: &str, serial: &str, vaults: &[&str]) -> KeyEntry {
+        KeyEntry::Yubikey {
+            label: label.to_string(),
+            created_at: Utc::now(),
+            last_used: None,
+            serial: serial.to_string(),
+            slot: 1,
+            piv_slot: 82,
+            recipient: format!("age1yubikey1{serial}mock"),
+            identity_tag: format!("AGE-PLUGIN-YUBIKEY-{serial}"),
+            model: "YubiKey 5".to_string(),
+            firmware_version: None,
+            recovery_code_hash: String::new(),
+            lifecycle_status: if vaults.is_empty() {
+                KeyLifecycleStatus::PreActivation
+            } else {
+                KeyLifecycleStatus::Active
+            },
+            status_history: vec![StatusHistoryEntry::new(
+                KeyLifecycleStatus::PreActivation,
+                "Registered",
+                "test",
+            )],
+            vault_associations: vaults.iter().map(|v| v.to_string()).collect(),
+            deactivated_at: None,
+            previous_lifecycle_status: None,
+            last_reader: None,
+        }
+    }
+
+    fn vault(vault_id: &str, registry: &KeyRegistry) -> VaultMetadata {
+        let device_info = DeviceInfo {
+            machine_id: "test".to_string(),
+            machine_label: "test".to_string(),
+            created_at: Utc::now(),
+            app_version: "2.0.0".to_string(),
+        };
+        let recipients = ["lost", "backup"]
+            .into_iter()
+            .map(|key_id| {
+                KeyRegistryService::key_entry_to_recipient(
+                    key_id,
+                    registry.get_key(key_id).unwrap(),
+                )
+                .unwrap()
+            })
+            .collect();
+        VaultMetadata::new(
+            vault_id.to_string(),
+            vault_id.to_string(),
+            None,
+            vault_id.to_string(),
+            &device_info,
+            None,
+            recipients,
+            vec![],
+            0,
+            0,
+        )
+    }
+
+    /// In-memory backend; re-encryption of a vault fails while it is listed
+    /// in `failing`
+    struct MockBackend {
+        registry: Mutex<KeyRegistry>,
+        vaults: Mutex<HashMap<String, VaultMetadata>>,
+        failing: Mutex<HashSet<String>>,
+        reencrypted: Mutex<Vec<(String, Vec<String>)>>,
+    }
+
+    impl MockBackend {
+        fn new() -> Self {
+            let mut registry = KeyRegistry::new();
+            for (key_id, entry) in [
+                ("lost", yubikey("Lost", LOST_SERIAL, &["alpha", "beta"])),
+                ("backup", yubikey("Backup", "33333333", &["alpha", "beta"])),
+                ("new", yubikey("New", NEW_SERIAL, &[])),
+            ] {
+                registry.register_key(key_id.to_string(), entry).unwrap();
+            }
+            let vaults = ["alpha", "beta"]
+                .into_iter()
+                .map(|id| (id.to_string(), vault(id, &registry)))
+                .collect();
+            Self {
+                registry: Mutex::new(registry),
+                vaults: Mutex::new(vaults),
+                failing: Mutex::new(HashSet::from(["beta".to_string()])),
+                reencrypted: Mutex::new(Vec::new()),
+            }
+        }
+    }
+
+    #[async_trait]
+    impl ReplacementBackend for std::sync::Arc<MockBackend> {
+        async fn connected_serials(&self) -> Result<Vec<String>> {
+            Ok(vec![NEW_SERIAL.to_string()])
+        }
+
+        fn load_registry(&self) -> Result<KeyRegistry> {
+            Ok(self.registry.lock().unwrap().clone())
+        }
+
+        async fn load_vault(&self, vault_id: &str) -> Result<VaultMetadata> {
+            self.vaults
+                .lock()
+                .unwrap()
+                .get(vault_id)
+                .cloned()
+                .ok_or_else(|| KeyManagementError::VaultNotFound(vault_id.to_string()))
+        }
+
+        fn reencrypt_latest_archive(
+            &self,
+            vault: &VaultMetadata,
+            recipients: &[String],
+            _unlock: &UnlockKey,
+        ) -> Result<Option<String>> {
+            if self.failing.lock().unwrap().contains(vault.vault_id()) {
+                return Err(KeyManagementError::StorageError("disk full".to_string()));
+            }
+            self.reencrypted
+                .lock()
+                .unwrap()
+                .push((vault.vault_id().to_string(), recipients.to_vec()));
+            Ok(Some(format!("{}.age", vault.vault_id())))
+        }
+
+        fn save_vault_and_registry(
+            &self,
+            vault: &VaultMetadata,
+            registry: &KeyRegistry,
+        ) -> Result<()> {
+            self.vaults
+                .lock()
+                .unwrap()
+                .insert(vault.vault_id().to_string(), vault.clone());
+            self.save_registry(registry)
+        }
+
+        fn save_registry(&self, registry: &KeyRegistry) -> Result<()> {
+            *self.registry.lock().unwrap() = registry.clone();
+            Ok(())
+        }
+
+        fn recorded_encryptions(&self, _vault_id: &str) -> usize {
+            3
+        }
+    }
+
+    fn unlock() -> UnlockKey {
+        UnlockKey {
+            key_id: "backup".to_string(),
+            secret: SecretString::from("123456".to_string()),
+        }
+    }
+
+    fn key_ids(recipients: &[RecipientInfo]) -> Vec<&str> {
+        recipients.iter().map(|r| r.key_id.as_str()).collect()
+    }
+
+    #[tokio::test]
+    async fn test_two_vaults_one_fails_then_retry_succeeds() {
+        let backend = std::sync::Arc::new(MockBackend::new());
+        let service = YubiKeyReplacementService::with_backend(Box::new(backend.clone()));
+        let mut progress = ProgressManager::new("replace".to_string(), 100);
+
+        let report = service
+            .replace(
+                "lost",
+                &VaultSelection::AllAssociated,
+                &unlock(),
+                &mut progress,
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(report.replacement_key_id, "new");
+        assert!(report.lost_key_revoked);
+        assert_eq!(report.exposed_vault_ids(), vec!["beta"]);
+        assert_eq!(
+            report.vaults[0].status,
+            VaultReplacementStatus::Reencrypted {
+                archive_name: "alpha.age".to_string()
+            }
+        );
+        assert_eq!(report.vaults[0].earlier_encryptions, 3);
+        assert!(report.old_archives_notice.contains("lost YubiKey"));
+
+        // Alpha was re-encrypted to the backup and the replacement only
+        let reencrypted = backend.reencrypted.lock().unwrap().clone();
+        assert_eq!(reencrypted.len(), 1);
+        assert!(!reencrypted[0].1.iter().any(|r| r.contains(LOST_SERIAL)));
+        assert!(reencrypted[0].1.iter().any(|r| r.contains(NEW_SERIAL)));
+
+        // Beta is untouched and still lists the lost key
+        {
+            let vaults = backend.vaults.lock().unwrap();
+            assert_eq!(key_ids(vaults["alpha"].recipients()), vec!["backup", "new"]);
+            assert_eq!(key_ids(vaults["beta"].recipients()), vec!["lost", "backup"]);
+            let registry = backend.registry.lock().unwrap();
+            let lost = registry.get_key("lost").unwrap();
+            assert_eq!(lost.lifecycle_status(), KeyLifecycleStatus::Revoked);
+            assert_eq!(lost.replaced_by(), Some("new"));
+            assert_eq!(lost.vault_associations(), ["beta".to_string()]);
+            let new = registry.get_key("new").unwrap();
+            assert_eq!(new.lifecycle_status(), KeyLifecycleStatus::Active);
+            assert_eq!(new.vault_associations(), ["alpha".to_string()]);
+        }
+
+        // Retry once the failure is resolved; only beta is left
+        backend.failing.lock().unwrap().clear();
+        let retry = service
+            .replace(
+                "lost",
+                &VaultSelection::AllAssociated,
+                &unlock(),
+                &mut progress,
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(retry.vaults.len(), 1);
+        assert_eq!(retry.vaults[0].vault_id, "beta");
+        assert!(retry.exposed_vault_ids().is_empty());
+        assert!(retry.lost_key_revoked);
+        let vaults = backend.vaults.lock().unwrap();
+        assert_eq!(key_ids(vaults["beta"].recipients()), vec!["backup", "new"]);
+        let registry = backend.registry.lock().unwrap();
+        assert!(
+            registry
+                .get_key("lost")
+                .unwrap()
+                .vault_associations()
+                .is_empty()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_rejects_lost_key_as_unlock_key_and_unrelated_vaults() {
+        let backend = std::sync::Arc::new(MockBackend::new());
+        let service = YubiKeyReplacementService::with_backend(Box::new(backend));
+        let mut progress = ProgressManager::new("replace".to_string(), 100);
+
+        let lost_unlock = UnlockKey {
+            key_id: "lost".to_string(),
+            secret: SecretString::from("123456".to_string()),
+        };
+        let result = service
+            .replace(
+                "lost",
+                &VaultSelection::AllAssociated,
+                &lost_unlock,
+                &mut progress,
+            )
+            .await;
+        assert!(matches!(
+            result,
+            Err(KeyManagementError::InvalidOperation(_))
+        ));
+
+        let selection = VaultSelection::Selected(vec!["gamma".to_string()]);
+        let result = service
+            .replace("lost", &selection, &unlock(), &mut progress)
+            .await;
+        assert!(matches!(
+            result,
+            Err(KeyManagementError::InvalidOperation(_))
+        ));
+    }
+}
@@ -0,0 +1,196 @@
+//! Contacts: other people's age recipients kept for encryption
+//!
+//! A contact is someone whose recipient the user got from that person's own
+//! setup. Contacts aren't vault keys: they are added to a single encryption
+//! on request, recorded separately in the manifest, and can never decrypt.
+
+use super::recipient_validation::{RecipientValidationError, validate_label, validate_public_key};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+/// Prefix of every contact ID, so a contact is never mistaken for a key
+pub const CONTACT_ID_PREFIX: &str = "contact-";
+
+/// Longest note kept with a contact
+pub const MAX_CONTACT_NOTE_LENGTH: usize = 500;
+
+/// A contact as stored in the key registry
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Contact {
+    pub name: String,
+    /// age recipient (age1...)
+    pub recipient: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+    pub added_at: DateTime<Utc>,
+}
+
+impl Contact {
+    /// Validate and normalize a new contact
+    pub fn new(
+        name: &str,
+        recipient: &str,
+        note: Option<&str>,
+        added_at: DateTime<Utc>,
+    ) -> Result<Self, ContactError> {
+        let name = validate_label(name).map_err(ContactError::InvalidName)?;
+        let recipient = recipient.trim();
+        validate_public_key(recipient).map_err(ContactError::InvalidRecipient)?;
+
+        let note = note.map(str::trim).filter(|note| !note.is_empty());
+        if note.is_some_and(|note| note.chars().count() > MAX_CONTACT_NOTE_LENGTH) {
+            return Err(ContactError::NoteTooLong);
+        }
+
+        Ok(Self {
+            name,
+            recipient: recipient.to_string(),
+            note: note.map(str::to_string),
+            added_at,
+        })
+    }
+
+    pub fn fingerprint(&self) -> String {
+        recipient_fingerprint(&self.recipient)
+    }
+}
+
+/// Short fingerprint of a recipient for comparing it with its owner by eye
+///
+/// First 8 bytes of its SHA-256 as four groups of four hex digits.
+pub fn recipient_fingerprint(recipient: &str) -> String {
+    let digest = Sha256::digest(recipient.trim().as_bytes());
+    digest[..8]
+        .chunks(2)
+        .map(hex::encode_upper)
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+/// Whether an ID names a contact rather than a key
+pub fn is_contact_id(id: &str) -> bool {
+    id.starts_with(CONTACT_ID_PREFIX)
+}
+
+/// ID for a new contact, from its name and the time it was added
+pub fn generate_contact_id(name: &str, added_at: DateTime<Utc>) -> String {
+    let sanitized: String = name
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() {
+                c.to_ascii_lowercase()
+            } else {
+                '-'
+            }
+        })
+        .collect();
+    format!(
+        "{CONTACT_ID_PREFIX}{}-{}",
+        sanitized.trim_matches('-'),
+        added_at.timestamp_millis()
+    )
+}
+
+/// A contact as shown to the UI
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, specta::Type)]
+pub struct ContactInfo {
+    pub id: String,
+    pub name: String,
+    pub recipient: String,
+    /// Read this to the contact to confirm the recipient is theirs
+    pub fingerprint: String,
+    pub note: Option<String>,
+    pub added_at: DateTime<Utc>,
+}
+
+impl ContactInfo {
+    pub fn new(id: &str, contact: &Contact) -> Self {
+        Self {
+            id: id.to_string(),
+            name: contact.name.clone(),
+            recipient: contact.recipient.clone(),
+            fingerprint: contact.fingerprint(),
+            note: contact.note.clone(),
+            added_at: contact.added_at,
+        }
+    }
+}
+
+/// Contact book errors
+#[derive(Debug, thiserror::Error)]
+pub enum ContactError {
+    #[error("Invalid contact name: {0}")]
+    InvalidName(RecipientValidationError),
+
+    #[error("Invalid contact recipient: {0}")]
+    InvalidRecipient(RecipientValidationError),
+
+    #[error("Contact note exceeds {MAX_CONTACT_NOTE_LENGTH} characters")]
+    NoteTooLong,
+
+    #[error("This recipient is your own key '{0}'; vault keys are already encrypted to")]
+    OwnKey(String),
+
+    #[error("This recipient is already saved as '{0}'")]
+    DuplicateRecipient(String),
+
+    #[error("Contact not found: {0}")]
+    NotFound(String),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RECIPIENT: &str = "age1ql3z7hjy54pw3hyww5ayyfg7zqgvc7w3j2elw8zmrj2kg5sfn9aqmcac8p";
+
+    #[test]
+    fn test_new_contact_is_normalized() {
+        let contact =
+            Contact::new("  Sam ", &format!(" {RECIPIENT}\n"), Some("  "), Utc::now()).unwrap();
+        assert_eq!(contact.name, "Sam");
+        assert_eq!(contact.recipient, RECIPIENT);
+        assert_eq!(contact.note, None);
+    }
+
+    #[test]
+    fn test_invalid_contacts_are_rejected() {
+        assert!(matches!(
+            Contact::new("Sam", "age1short", None, Utc::now()),
+            Err(ContactError::InvalidRecipient(_))
+        ));
+        assert!(matches!(
+            Contact::new("", RECIPIENT, None, Utc::now()),
+            Err(ContactError::InvalidName(_))
+        ));
+        let note = "x".repeat(MAX_CONTACT_NOTE_LENGTH + 1);
+        assert!(matches!(
+            Contact::new("Sam", RECIPIENT, Some(&note), Utc::now()),
+            Err(ContactError::NoteTooLong)
+        ));
+    }
+
+    #[test]
+    fn test_fingerprint_format() {
+        let fingerprint = recipient_fingerprint(RECIPIENT);
+        assert_eq!(fingerprint.len(), 19);
+        assert_eq!(fingerprint.matches('-').count(), 3);
+        assert_eq!(
+            fingerprint,
+            recipient_fingerprint(&format!(" {RECIPIENT} "))
+        );
+        assert_ne!(
+            fingerprint,
+            recipient_fingerprint(&RECIPIENT.replace('p', "q"))
+        );
+    }
+
+    #[test]
+    fn test_contact_ids() {
+        let id = generate_contact_id("Sam O'Neil", Utc::now());
+        assert!(id.starts_with("contact-sam-o-neil-"));
+        assert!(is_contact_id(&id));
+        assert!(!is_contact_id("recipient-sam-1"));
+    }
+}
@@ -29,6 +29,10 @@ pub enum KeyLifecycleStatus {
 
     /// Security breach detected, immediate deactivation required
     Compromised,
+
+    /// Lost and replaced by another key; can no longer be attached, but
+    /// archives encrypted before the replacement still list it
+    Revoked,
 }
 
 impl KeyLifecycleStatus {
@@ -43,11 +47,13 @@ pub fn can_transition_to(&self, target: KeyLifecycleStatus) -> bool {
             (KeyLifecycleStatus::Active, KeyLifecycleStatus::Suspended) => true,
             (KeyLifecycleStatus::Active, KeyLifecycleStatus::Deactivated) => true,
             (KeyLifecycleStatus::Active, KeyLifecycleStatus::Compromised) => true,
+            (KeyLifecycleStatus::Active, KeyLifecycleStatus::Revoked) => true,
 
             // Suspended transitions
             (KeyLifecycleStatus::Suspended, KeyLifecycleStatus::Active) => true,
             (KeyLifecycleStatus::Suspended, KeyLifecycleStatus::Deactivated) => true,
             (KeyLifecycleStatus::Suspended, KeyLifecycleStatus::Compromised) => true,
+            (KeyLifecycleStatus::Suspended, KeyLifecycleStatus::Revoked) => true,
 
             // Deactivated transitions
             (KeyLifecycleStatus::Deactivated, KeyLifecycleStatus::Destroyed) => true,
@@ -55,6 +61,9 @@ pub fn can_transition_to(&self, target: KeyLifecycleStatus) -> bool {
             // Compromised transitions
             (KeyLifecycleStatus::Compromised, KeyLifecycleStatus::Destroyed) => true,
 
+            // Revoked transitions
+            (KeyLifecycleStatus::Revoked, KeyLifecycleStatus::Destroyed) => true,
+
             // All other transitions are invalid
             _ => false,
         }
@@ -69,6 +78,7 @@ pub fn description(&self) -> &str {
             KeyLifecycleStatus::Deactivated => "Key is permanently disabled",
             KeyLifecycleStatus::Destroyed => "Key has been cryptographically destroyed",
             KeyLifecycleStatus::Compromised => "Key has been compromised and must not be used",
+            KeyLifecycleStatus::Revoked => "Key was lost and has been replaced",
         }
     }
 
@@ -81,6 +91,7 @@ pub fn display_text(&self) -> &str {
             KeyLifecycleStatus::Deactivated => "Deactivated",
             KeyLifecycleStatus::Destroyed => "Destroyed",
             KeyLifecycleStatus::Compromised => "Compromised",
+            KeyLifecycleStatus::Revoked => "Revoked",
         }
     }
 
@@ -222,6 +233,12 @@ fn test_valid_transitions() {
 
         // Compromised transitions
         assert!(KeyLifecycleStatus::Compromised.can_transition_to(KeyLifecycleStatus::Destroyed));
+
+        // Revoked transitions
+        assert!(KeyLifecycleStatus::Active.can_transition_to(KeyLifecycleStatus::Revoked));
+        assert!(KeyLifecycleStatus::Suspended.can_transition_to(KeyLifecycleStatus::Revoked));
+        assert!(KeyLifecycleStatus::Revoked.can_transition_to(KeyLifecycleStatus::Destroyed));
+        assert!(!KeyLifecycleStatus::Revoked.can_transition_to(KeyLifecycleStatus::Active));
     }
 
     #[test]
@@ -272,6 +289,7 @@ fn test_vault_attachment_eligibility() {
         assert!(!KeyLifecycleStatus::Deactivated.can_attach_to_vault());
         assert!(!KeyLifecycleStatus::Destroyed.can_attach_to_vault());
         assert!(!KeyLifecycleStatus::Compromised.can_attach_to_vault());
+        assert!(!KeyLifecycleStatus::Revoked.can_attach_to_vault());
     }
 
     #[test]
@@ -0,0 +1,235 @@
+//! Location of passphrase key files kept outside the keys directory
+//!
+//! Removable drives don't always mount at the same path: macOS mounts a second
+//! drive labelled `KEYS` at `/Volumes/KEYS 1`, and Linux desktops mount under a
+//! per-user directory. A location therefore records which volume the key file
+//! is on (UUID and label) and its path within that volume, with the last
+//! absolute path kept as a fallback.
+
+use crate::types::Sensitive;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// A filesystem currently mounted on this machine
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MountedVolume {
+    /// Mount point
+    pub root: PathBuf,
+    /// Volume name as shown to the user
+    pub label: String,
+    /// Filesystem UUID, when the platform exposes one
+    pub uuid: Option<String>,
+}
+
+/// The volume a key file was linked on
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeyVolume {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub uuid: Option<String>,
+    pub label: String,
+    /// Path of the key file below the volume's mount point
+    pub relative_path: PathBuf,
+}
+
+impl KeyVolume {
+    fn matches_uuid(&self, mounted: &MountedVolume) -> bool {
+        match (&self.uuid, &mounted.uuid) {
+            (Some(ours), Some(theirs)) => ours.eq_ignore_ascii_case(theirs),
+            _ => false,
+        }
+    }
+
+    /// Same label, allowing the numeric suffix added when a mount point name
+    /// is taken (`KEYS 1`, `KEYS1`). A drive with a different UUID isn't ours,
+    /// whatever it's called.
+    fn matches_label(&self, mounted: &MountedVolume) -> bool {
+        if self.uuid.is_some() && mounted.uuid.is_some() && !self.matches_uuid(mounted) {
+            return false;
+        }
+
+        let name = mounted.label.as_str();
+        if name.eq_ignore_ascii_case(&self.label) {
+            return true;
+        }
+
+        name.get(..self.label.len())
+            .filter(|prefix| prefix.eq_ignore_ascii_case(&self.label))
+            .map(|_| name[self.label.len()..].trim_start())
+            .is_some_and(|suffix| !suffix.is_empty() && suffix.bytes().all(|b| b.is_ascii_digit()))
+    }
+}
+
+/// Where a relinked passphrase key file lives
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeyFileLocation {
+    /// Set when the file was on a mounted volume at link time
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub volume: Option<KeyVolume>,
+    pub last_known_path: PathBuf,
+}
+
+impl fmt::Debug for KeyVolume {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("KeyVolume")
+            .field("uuid", &self.uuid)
+            .field("label", &self.label)
+            .field("relative_path", &Sensitive::new(&self.relative_path))
+            .finish()
+    }
+}
+
+impl fmt::Debug for KeyFileLocation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("KeyFileLocation")
+            .field("volume", &self.volume)
+            .field("last_known_path", &Sensitive::new(&self.last_known_path))
+            .finish()
+    }
+}
+
+impl KeyFileLocation {
+    /// Location of `path`, tied to the innermost mounted volume containing it
+    pub fn for_path(path: &Path, volumes: &[MountedVolume]) -> Self {
+        let volume = volumes
+            .iter()
+            .filter(|mounted| path.starts_with(&mounted.root))
+            .max_by_key(|mounted| mounted.root.components().count())
+            .and_then(|mounted| {
+                Some(KeyVolume {
+                    uuid: mounted.uuid.clone(),
+                    label: mounted.label.clone(),
+                    relative_path: path.strip_prefix(&mounted.root).ok()?.to_path_buf(),
+                })
+            });
+
+        Self {
+            volume,
+            last_known_path: path.to_path_buf(),
+        }
+    }
+
+    /// Label of the volume holding the key file, if it's on one
+    pub fn volume_label(&self) -> Option<&str> {
+        self.volume.as_ref().map(|volume| volume.label.as_str())
+    }
+
+    /// Current path of the key file, or `None` if it can't be found
+    ///
+    /// Mounted volumes are searched by UUID and then by label, so a drive
+    /// that came back at a different mount point is still found. The last
+    /// known path is only tried after that.
+    pub fn resolve(&self, volumes: &[MountedVolume]) -> Option<PathBuf> {
+        if let Some(volume) = &self.volume {
+            let by_uuid = volumes
+                .iter()
+                .filter(|mounted| volume.matches_uuid(mounted));
+            let by_label = volumes
+                .iter()
+                .filter(|mounted| volume.matches_label(mounted));
+
+            let found = by_uuid
+                .chain(by_label)
+                .map(|mounted| mounted.root.join(&volume.relative_path))
+                .find(|candidate| candidate.is_file());
+            if found.is_some() {
+                return found;
+            }
+        }
+
+        self.last_known_path
+            .is_file()
+            .then(|| self.last_known_path.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn mounted(root: &Path, label: &str, uuid: Option<&str>) -> MountedVolume {
+        MountedVolume {
+            root: root.to_path_buf(),
+            label: label.to_string(),
+            uuid: uuid.map(str::to_string),
+        }
+    }
+
+    fn write_key(root: &Path) -> PathBuf {
+        let path = root.join("barqly").join("laptop.agekey.enc");
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, b"age-encryption.org/v1").unwrap();
+        path
+    }
+
+    #[test]
+    fn test_for_path_records_innermost_volume() {
+        let drive = TempDir::new().unwrap();
+        let key_path = write_key(drive.path());
+        let volumes = [
+            mounted(Path::new("/"), "root", None),
+            mounted(drive.path(), "KEYS", Some("1A2B-3C4D")),
+        ];
+
+        let location = KeyFileLocation::for_path(&key_path, &volumes);
+        let volume = location.volume.unwrap();
+
+        assert_eq!(volume.label, "KEYS");
+        assert_eq!(volume.uuid.as_deref(), Some("1A2B-3C4D"));
+        assert_eq!(volume.relative_path, Path::new("barqly/laptop.agekey.enc"));
+        assert_eq!(location.last_known_path, key_path);
+    }
+
+    #[test]
+    fn test_label_suffix_matching() {
+        let volume = KeyVolume {
+            uuid: None,
+            label: "KEYS".to_string(),
+            relative_path: PathBuf::from("k.agekey.enc"),
+        };
+        let at = |label: &str| mounted(Path::new("/Volumes"), label, None);
+
+        assert!(volume.matches_label(&at("KEYS")));
+        assert!(volume.matches_label(&at("keys")));
+        assert!(volume.matches_label(&at("KEYS 1")));
+        assert!(volume.matches_label(&at("KEYS1")));
+        assert!(!volume.matches_label(&at("KEYS-backup")));
+        assert!(!volume.matches_label(&at("KEY")));
+    }
+
+    #[test]
+    fn test_resolve_falls_back_to_last_known_path() {
+        let drive = TempDir::new().unwrap();
+        let key_path = write_key(drive.path());
+        let location = KeyFileLocation {
+            volume: Some(KeyVolume {
+                uuid: None,
+                label: "KEYS".to_string(),
+                relative_path: PathBuf::from("barqly/laptop.agekey.enc"),
+            }),
+            last_known_path: key_path.clone(),
+        };
+
+        assert_eq!(location.resolve(&[]), Some(key_path.clone()));
+
+        fs::remove_file(&key_path).unwrap();
+        assert_eq!(location.resolve(&[]), None);
+    }
+
+    #[test]
+    fn test_resolve_skips_same_label_on_other_drive() {
+        let ours = TempDir::new().unwrap();
+        let other = TempDir::new().unwrap();
+        let key_path = write_key(ours.path());
+        write_key(other.path());
+
+        let location =
+            KeyFileLocation::for_path(&key_path, &[mounted(ours.path(), "KEYS", Some("AAAA"))]);
+        fs::remove_file(&key_path).unwrap();
+
+        let volumes = [mounted(other.path(), "KEYS", Some("BBBB"))];
+        assert_eq!(location.resolve(&volumes), None);
+    }
+}
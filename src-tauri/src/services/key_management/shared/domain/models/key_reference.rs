@@ -60,6 +60,16 @@ pub enum KeyType {
     /// Recipient - public key only (user does NOT have private key)
     /// Used for encrypting to other people's keys
     Recipient,
+
+    /// Reserved YubiKey slot - hardware not registered yet, no recipient
+    YubiKeyPending,
+
+    /// Contact - external, never attached to a vault and never decrypts
+    /// Chosen per encryption with `contact_ids`
+    Contact {
+        /// Fingerprint of the contact's recipient
+        fingerprint: String,
+    },
 }
 
 /// Filter options for key listing operations
@@ -168,6 +178,11 @@ pub fn from_registry_entry(
                 last_used,
                 ..
             } => (KeyType::Recipient, label.clone(), *created_at, *last_used),
+            crate::services::key_management::shared::KeyEntry::YubikeyPending {
+                label,
+                created_at,
+                ..
+            } => (KeyType::YubiKeyPending, label.clone(), *created_at, None),
         };
 
         Self {
@@ -195,6 +210,16 @@ pub fn is_recipient(&self) -> bool {
         matches!(self.key_type, KeyType::Recipient)
     }
 
+    /// Check if this is a reserved YubiKey slot
+    pub fn is_yubikey_pending(&self) -> bool {
+        matches!(self.key_type, KeyType::YubiKeyPending)
+    }
+
+    /// Check if this is a contact (external, encrypt-only)
+    pub fn is_contact(&self) -> bool {
+        matches!(self.key_type, KeyType::Contact { .. })
+    }
+
     /// Check if this is an owned key (user has private key)
     pub fn is_owned_key(&self) -> bool {
         matches!(
@@ -0,0 +1,188 @@
+//! Key removal planning models
+//!
+//! Before a key is deactivated or deleted, every recorded archive is checked
+//! for whether it can still be opened without it. An archive the key is the
+//! only recipient of is lost for good once the key is gone, so removing the
+//! key then takes the plan's hash, proving the user was shown the plan.
+
+use crate::types::ByteSize;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+/// What removing the key does to one archive
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, specta::Type)]
+#[serde(rename_all = "snake_case")]
+pub enum RemovalImpact {
+    /// The key isn't one of the archive's recipients
+    Unaffected,
+    /// The key is one of several recipients; the others still open it
+    Degraded,
+    /// The key is the only recipient; nothing else opens the archive
+    Orphaned,
+    /// Neither the recorded recipients nor the header tell
+    Unknown,
+}
+
+impl RemovalImpact {
+    /// Impact on an archive encrypted to `recipients` keys
+    pub fn of(is_recipient: bool, recipients: usize) -> Self {
+        match (is_recipient, recipients) {
+            (false, _) => Self::Unaffected,
+            (true, 0 | 1) => Self::Orphaned,
+            (true, _) => Self::Degraded,
+        }
+    }
+}
+
+/// Where an archive's recipients were learned from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, specta::Type)]
+#[serde(rename_all = "snake_case")]
+pub enum RecipientSource {
+    /// Recorded in the archive index when the archive was written
+    Recorded,
+    /// Read from the archive's age header, for archives recorded earlier
+    Header,
+    /// Neither was available
+    Unavailable,
+}
+
+/// One archive in a removal plan
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, specta::Type)]
+pub struct ArchiveRemovalImpact {
+    pub archive_id: String,
+    pub archive_name: String,
+    pub created_at: DateTime<Utc>,
+    /// The archive file still holds this encryption; earlier ones only
+    /// survive in copies made elsewhere
+    pub current: bool,
+    /// Size of the archive file, when it's on this device
+    pub size: Option<ByteSize>,
+    pub impact: RemovalImpact,
+    /// Keys the archive is encrypted to, when known
+    pub recipient_count: Option<usize>,
+    pub source: RecipientSource,
+}
+
+/// A vault's archives in a removal plan, oldest first
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, specta::Type)]
+pub struct VaultRemovalImpact {
+    pub vault_id: String,
+    pub vault_name: String,
+    pub archives: Vec<ArchiveRemovalImpact>,
+}
+
+impl VaultRemovalImpact {
+    /// Archives of the vault with `impact`
+    pub fn count(&self, impact: RemovalImpact) -> usize {
+        self.archives
+            .iter()
+            .filter(|archive| archive.impact == impact)
+            .count()
+    }
+}
+
+/// What the user should do before removing the key
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, specta::Type)]
+#[serde(rename_all = "snake_case")]
+pub enum RemovalRecommendation {
+    /// No archive is encrypted to the key
+    SafeToRemove,
+    /// Every archive still opens, but with fewer keys
+    ReducesRedundancy,
+    /// Some archives couldn't be checked; open them with another key first
+    CheckUnknownArchives,
+    /// Re-encrypt the orphaned archives to another key before removing
+    ReencryptFirst,
+}
+
+/// Every recorded archive's fate if a key were removed now
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, specta::Type)]
+pub struct KeyRemovalPlan {
+    pub key_id: String,
+    pub key_label: String,
+    pub vaults: Vec<VaultRemovalImpact>,
+    pub orphaned: usize,
+    pub degraded: usize,
+    pub unaffected: usize,
+    pub unknown: usize,
+    /// Size of the orphaned archives found on this device
+    pub orphaned_size: ByteSize,
+    pub recommendation: RemovalRecommendation,
+    /// Passed back to confirm removing the key; changes with any archive's
+    /// impact, so a plan made before a new encryption no longer confirms
+    pub plan_hash: String,
+}
+
+impl KeyRemovalPlan {
+    pub fn new(key_id: &str, key_label: &str, vaults: Vec<VaultRemovalImpact>) -> Self {
+        let count = |impact| -> usize { vaults.iter().map(|vault| vault.count(impact)).sum() };
+        let orphaned = count(RemovalImpact::Orphaned);
+        let degraded = count(RemovalImpact::Degraded);
+        let unaffected = count(RemovalImpact::Unaffected);
+        let unknown = count(RemovalImpact::Unknown);
+        let orphaned_size = vaults
+            .iter()
+            .flat_map(|vault| &vault.archives)
+            .filter(|archive| archive.impact == RemovalImpact::Orphaned)
+            .filter_map(|archive| archive.size)
+            .sum();
+
+        let recommendation = if orphaned > 0 {
+            RemovalRecommendation::ReencryptFirst
+        } else if unknown > 0 {
+            RemovalRecommendation::CheckUnknownArchives
+        } else if degraded > 0 {
+            RemovalRecommendation::ReducesRedundancy
+        } else {
+            RemovalRecommendation::SafeToRemove
+        };
+
+        Self {
+            plan_hash: plan_hash(key_id, &vaults),
+            key_id: key_id.to_string(),
+            key_label: key_label.to_string(),
+            vaults,
+            orphaned,
+            degraded,
+            unaffected,
+            unknown,
+            orphaned_size,
+            recommendation,
+        }
+    }
+
+    /// Removing the key leaves archives nothing else can open
+    pub fn requires_confirmation(&self) -> bool {
+        self.orphaned > 0
+    }
+
+    /// `plan_hash` is this plan's, or no confirmation is needed
+    pub fn is_confirmed_by(&self, plan_hash: Option<&str>) -> bool {
+        !self.requires_confirmation() || plan_hash == Some(self.plan_hash.as_str())
+    }
+}
+
+/// SHA-256 over the key and each archive's impact, in a fixed order
+fn plan_hash(key_id: &str, vaults: &[VaultRemovalImpact]) -> String {
+    let mut lines: Vec<String> = vaults
+        .iter()
+        .flat_map(|vault| {
+            vault.archives.iter().map(move |archive| {
+                format!(
+                    "{}/{}:{:?}",
+                    vault.vault_id, archive.archive_id, archive.impact
+                )
+            })
+        })
+        .collect();
+    lines.sort();
+
+    let mut hasher = Sha256::new();
+    hasher.update(key_id.as_bytes());
+    for line in lines {
+        hasher.update(b"\n");
+        hasher.update(line.as_bytes());
+    }
+    hex::encode(hasher.finalize())
+}
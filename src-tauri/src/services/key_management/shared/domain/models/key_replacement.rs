@@ -0,0 +1,76 @@
+//! Lost YubiKey replacement models
+//!
+//! Which vaults a replacement covers, and the report telling the user where
+//! the lost key can still open data.
+
+use serde::{Deserialize, Serialize};
+
+/// Vaults to move from a lost key to its replacement
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+#[serde(tag = "mode", content = "vault_ids", rename_all = "snake_case")]
+pub enum VaultSelection {
+    /// Every vault the lost key is still attached to
+    AllAssociated,
+    /// Only these vaults
+    Selected(Vec<String>),
+}
+
+/// Outcome of moving one vault to the replacement key
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, specta::Type)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum VaultReplacementStatus {
+    /// Latest archive re-encrypted and the lost key removed from the vault
+    Reencrypted { archive_name: String },
+    /// No archive yet; the lost key was removed from the vault
+    NoArchive,
+    /// Nothing changed: the vault still lists the lost key, and its latest
+    /// archive can still be opened with it
+    StillExposed { error: String },
+}
+
+/// One vault in a replacement report
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, specta::Type)]
+pub struct VaultReplacementOutcome {
+    pub vault_id: String,
+    pub vault_name: String,
+    pub status: VaultReplacementStatus,
+    /// Earlier encryptions recorded for the vault, which stay readable by
+    /// the lost key wherever copies of them exist
+    pub earlier_encryptions: usize,
+}
+
+impl VaultReplacementOutcome {
+    pub fn is_exposed(&self) -> bool {
+        matches!(self.status, VaultReplacementStatus::StillExposed { .. })
+    }
+}
+
+/// Final report of a YubiKey replacement
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, specta::Type)]
+pub struct YubiKeyReplacementReport {
+    pub lost_key_id: String,
+    pub replacement_key_id: String,
+    pub replacement_label: String,
+    pub vaults: Vec<VaultReplacementOutcome>,
+    /// Whether the lost key is now marked revoked in the registry
+    pub lost_key_revoked: bool,
+    /// Reminder that archives from before the replacement still list the
+    /// lost key, so the user can decide whether to delete old copies
+    pub old_archives_notice: String,
+}
+
+impl YubiKeyReplacementReport {
+    /// Vaults whose re-encryption failed and should be retried
+    pub fn exposed_vault_ids(&self) -> Vec<&str> {
+        self.vaults
+            .iter()
+            .filter(|outcome| outcome.is_exposed())
+            .map(|outcome| outcome.vault_id.as_str())
+            .collect()
+    }
+}
+
+/// Notice included in every replacement report
+pub const OLD_ARCHIVES_NOTICE: &str = "Archives encrypted before this replacement, including \
+copies in backups or cloud folders, can still be opened with the lost YubiKey. Delete old \
+copies you no longer need.";
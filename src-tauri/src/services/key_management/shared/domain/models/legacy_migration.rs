@@ -0,0 +1,71 @@
+//! Legacy profile migration models
+//!
+//! Which historical layouts were found on disk and what the one-time
+//! migration into the current locations did with them.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Layout written by an older build
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+#[serde(rename_all = "snake_case")]
+pub enum LegacyLayout {
+    /// Pre-registry builds: one `<label>.agekey.meta` beside each
+    /// `<label>.agekey.enc`
+    KeyMetadataFiles,
+    /// First registry builds: `key-registry.json` with schema
+    /// `barqly.vault.registry/1`
+    EarlyRegistry,
+}
+
+/// A legacy profile found on disk
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+pub struct LegacySource {
+    pub layout: LegacyLayout,
+    /// Profile root, e.g. the old app data directory
+    pub path: String,
+}
+
+/// A legacy key that can't be brought over without overwriting current data
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+pub struct MigrationConflict {
+    pub key_id: String,
+    pub reason: String,
+}
+
+/// What the migration did
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+#[serde(rename_all = "snake_case")]
+pub enum MigrationStatus {
+    /// Legacy keys were written to the current locations
+    Migrated,
+    /// Every legacy key was already present; only the legacy files were
+    /// renamed
+    AlreadyPresent,
+    /// Nothing was written: current data disagrees with the legacy profile
+    Conflict,
+}
+
+/// Result of migrating legacy profiles, kept for the settings screen
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+pub struct MigrationReport {
+    pub status: MigrationStatus,
+    pub ran_at: DateTime<Utc>,
+    pub sources: Vec<LegacySource>,
+    /// Key IDs added to the registry
+    pub keys_migrated: Vec<String>,
+    /// Key IDs skipped because the registry already had them unchanged
+    pub keys_already_present: Vec<String>,
+    /// Encrypted key files copied into the keys directory
+    pub key_files_copied: usize,
+    /// Legacy files renamed with the `.migrated` suffix
+    pub renamed_files: Vec<String>,
+    /// Set when `status` is `Conflict`
+    pub conflicts: Vec<MigrationConflict>,
+}
+
+impl MigrationReport {
+    pub fn has_conflicts(&self) -> bool {
+        self.status == MigrationStatus::Conflict
+    }
+}
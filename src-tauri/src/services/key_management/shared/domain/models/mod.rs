@@ -1,7 +1,17 @@
+pub mod contact;
 pub mod key_lifecycle;
+pub mod key_location;
 pub mod key_reference;
+pub mod key_removal;
+pub mod key_replacement;
+pub mod legacy_migration;
 pub mod recipient_validation;
 
+pub use contact::*;
 pub use key_lifecycle::*;
+pub use key_location::*;
 pub use key_reference::*;
+pub use key_removal::*;
+pub use key_replacement::*;
+pub use legacy_migration::*;
 pub use recipient_validation::*;
@@ -0,0 +1,336 @@
+//! Key files on removable media
+//!
+//! Discovers mounted volumes and resolves relinked passphrase key files
+//! against them. See `KeyFileLocation` for how a location is recorded.
+
+use super::key_storage::{load_encrypted_key, validate_key_file};
+use crate::error::StorageError;
+use crate::services::key_management::shared::domain::models::key_location::{
+    KeyFileLocation, MountedVolume,
+};
+use crate::services::shared::infrastructure::path_management::get_keys_dir;
+use std::fs;
+use std::path::PathBuf;
+
+/// Source of the volumes currently mounted
+pub trait VolumeSource {
+    fn mounted_volumes(&self) -> Vec<MountedVolume>;
+}
+
+/// Removable and external volumes mounted on this machine
+///
+/// macOS lists `/Volumes`; Linux lists mounts under the usual removable
+/// media roots. Other platforms report none, so relinked keys there resolve
+/// through their last known path only.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SystemVolumes;
+
+impl VolumeSource for SystemVolumes {
+    fn mounted_volumes(&self) -> Vec<MountedVolume> {
+        system_volumes()
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn system_volumes() -> Vec<MountedVolume> {
+    let Ok(entries) = fs::read_dir("/Volumes") else {
+        return Vec::new();
+    };
+
+    entries
+        .flatten()
+        .filter(|entry| entry.path().is_dir())
+        .map(|entry| {
+            let root = entry.path();
+            MountedVolume {
+                label: entry.file_name().to_string_lossy().into_owned(),
+                uuid: macos_volume_uuid(&root),
+                root,
+            }
+        })
+        .collect()
+}
+
+/// Volume UUID as reported by `diskutil info`
+#[cfg(target_os = "macos")]
+fn macos_volume_uuid(root: &std::path::Path) -> Option<String> {
+    let output = std::process::Command::new("diskutil")
+        .arg("info")
+        .arg(root)
+        .output()
+        .ok()?;
+
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .find_map(|line| line.trim().strip_prefix("Volume UUID:"))
+        .map(|uuid| uuid.trim().to_string())
+        .filter(|uuid| !uuid.is_empty())
+}
+
+#[cfg(target_os = "linux")]
+const LINUX_REMOVABLE_MOUNT_ROOTS: &[&str] = &["/media", "/run/media", "/mnt"];
+
+#[cfg(target_os = "linux")]
+fn system_volumes() -> Vec<MountedVolume> {
+    use std::collections::HashMap;
+
+    let mounts = fs::read_to_string("/proc/self/mounts").unwrap_or_default();
+
+    // /dev/disk/by-uuid/<uuid> -> /dev/sdb1
+    let uuids: HashMap<PathBuf, String> = fs::read_dir("/dev/disk/by-uuid")
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter_map(|entry| {
+            let device = fs::canonicalize(entry.path()).ok()?;
+            Some((device, entry.file_name().to_string_lossy().into_owned()))
+        })
+        .collect();
+
+    mounts
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let device = fields.next()?;
+            let root = PathBuf::from(unescape_mount_field(fields.next()?));
+            if !LINUX_REMOVABLE_MOUNT_ROOTS
+                .iter()
+                .any(|base| root.starts_with(base) && root != std::path::Path::new(base))
+            {
+                return None;
+            }
+
+            Some(MountedVolume {
+                label: root.file_name()?.to_string_lossy().into_owned(),
+                uuid: fs::canonicalize(device)
+                    .ok()
+                    .and_then(|device| uuids.get(&device).cloned()),
+                root,
+            })
+        })
+        .collect()
+}
+
+/// Undo the octal escapes `/proc/self/mounts` uses for whitespace and `\`
+#[cfg(target_os = "linux")]
+fn unescape_mount_field(field: &str) -> String {
+    let bytes = field.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = bytes
+            .get(i + 1..i + 4)
+            .filter(|_| bytes[i] == b'\\')
+            .and_then(|digits| u8::from_str_radix(std::str::from_utf8(digits).ok()?, 8).ok());
+        match escaped {
+            Some(byte) => {
+                out.push(byte);
+                i += 4;
+            }
+            None => {
+                out.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "linux")))]
+fn system_volumes() -> Vec<MountedVolume> {
+    Vec::new()
+}
+
+/// Current path of a passphrase key file
+///
+/// Keys without a recorded location live in the keys directory. Volumes are
+/// only scanned for keys linked on one.
+///
+/// # Errors
+/// - `StorageError::KeyMediaNotPresent` if the key's drive isn't mounted
+/// - `StorageError::KeyNotFound` if a key linked off-volume has gone missing
+pub fn resolve_key_file(
+    key_filename: &str,
+    location: Option<&KeyFileLocation>,
+    volumes: &dyn VolumeSource,
+) -> Result<PathBuf, StorageError> {
+    let Some(location) = location else {
+        return Ok(get_keys_dir()?.join(key_filename));
+    };
+
+    let mounted = if location.volume.is_some() {
+        volumes.mounted_volumes()
+    } else {
+        Vec::new()
+    };
+
+    location
+        .resolve(&mounted)
+        .ok_or_else(|| match location.volume_label() {
+            Some(label) => StorageError::KeyMediaNotPresent {
+                volume_label: label.to_string(),
+            },
+            None => StorageError::KeyNotFound(location.last_known_path.display().to_string()),
+        })
+}
+
+/// Read a passphrase key's encrypted file, following its location if relinked
+///
+/// FAT and exFAT drives report every file as world-readable, so the 0600
+/// permission check only applies to key files that aren't on a volume.
+pub fn load_passphrase_key_file(
+    key_filename: &str,
+    location: Option<&KeyFileLocation>,
+) -> Result<Vec<u8>, StorageError> {
+    let Some(location) = location else {
+        return load_encrypted_key(key_filename);
+    };
+
+    let key_path = resolve_key_file(key_filename, Some(location), &SystemVolumes)?;
+    if location.volume.is_none() {
+        validate_key_file(&key_path)?;
+    }
+
+    fs::read(&key_path).map_err(StorageError::IoError)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::key_management::shared::domain::models::key_location::KeyVolume;
+    use std::path::Path;
+    use tempfile::TempDir;
+
+    /// Fixed set of mount points standing in for the system's
+    struct FakeVolumes(Vec<MountedVolume>);
+
+    impl VolumeSource for FakeVolumes {
+        fn mounted_volumes(&self) -> Vec<MountedVolume> {
+            self.0.clone()
+        }
+    }
+
+    fn volume_at(root: &Path, label: &str, uuid: Option<&str>) -> MountedVolume {
+        MountedVolume {
+            root: root.to_path_buf(),
+            label: label.to_string(),
+            uuid: uuid.map(str::to_string),
+        }
+    }
+
+    fn write_key(root: &Path) -> PathBuf {
+        let key_path = root.join("keys").join("laptop.agekey.enc");
+        fs::create_dir_all(key_path.parent().unwrap()).unwrap();
+        fs::write(&key_path, b"encrypted key").unwrap();
+        key_path
+    }
+
+    #[test]
+    fn test_resolves_drive_remounted_under_new_label() {
+        // Linked while mounted at .../KEYS, now mounted at .../KEYS 1
+        let mounts = TempDir::new().unwrap();
+        let first = mounts.path().join("KEYS");
+        let second = mounts.path().join("KEYS 1");
+        fs::create_dir_all(&first).unwrap();
+        let linked_path = write_key(&first);
+
+        let location = KeyFileLocation::for_path(&linked_path, &[volume_at(&first, "KEYS", None)]);
+
+        fs::rename(&first, &second).unwrap();
+        let volumes = FakeVolumes(vec![volume_at(&second, "KEYS 1", None)]);
+
+        let resolved = resolve_key_file("laptop.agekey.enc", Some(&location), &volumes).unwrap();
+        assert_eq!(resolved, second.join("keys").join("laptop.agekey.enc"));
+    }
+
+    #[test]
+    fn test_resolves_drive_by_uuid_after_relabel() {
+        let old_mount = TempDir::new().unwrap();
+        let new_mount = TempDir::new().unwrap();
+        let linked_path = write_key(old_mount.path());
+        let location = KeyFileLocation::for_path(
+            &linked_path,
+            &[volume_at(old_mount.path(), "KEYS", Some("1A2B-3C4D"))],
+        );
+
+        // Same drive, renamed and mounted elsewhere
+        fs::remove_file(&linked_path).unwrap();
+        write_key(new_mount.path());
+        let volumes = FakeVolumes(vec![
+            volume_at(old_mount.path(), "KEYS", Some("FFFF-0000")),
+            volume_at(new_mount.path(), "BACKUP", Some("1a2b-3c4d")),
+        ]);
+
+        let resolved = resolve_key_file("laptop.agekey.enc", Some(&location), &volumes).unwrap();
+        assert_eq!(resolved, new_mount.path().join("keys/laptop.agekey.enc"));
+    }
+
+    #[test]
+    fn test_missing_drive_reports_media_not_present() {
+        let mount = TempDir::new().unwrap();
+        let linked_path = write_key(mount.path());
+        let location =
+            KeyFileLocation::for_path(&linked_path, &[volume_at(mount.path(), "KEYS", None)]);
+        drop(mount);
+
+        let result = resolve_key_file("laptop.agekey.enc", Some(&location), &FakeVolumes(vec![]));
+
+        match result {
+            Err(StorageError::KeyMediaNotPresent { volume_label }) => {
+                assert_eq!(volume_label, "KEYS")
+            }
+            other => panic!("expected KeyMediaNotPresent, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_off_volume_location_uses_stored_path() {
+        let dir = TempDir::new().unwrap();
+        let key_path = write_key(dir.path());
+        let location = KeyFileLocation {
+            volume: None,
+            last_known_path: key_path.clone(),
+        };
+        let never_scanned = FakeVolumes(vec![volume_at(Path::new("/nonexistent"), "X", None)]);
+
+        let resolved =
+            resolve_key_file("laptop.agekey.enc", Some(&location), &never_scanned).unwrap();
+        assert_eq!(resolved, key_path);
+
+        fs::remove_file(&key_path).unwrap();
+        assert!(matches!(
+            resolve_key_file("laptop.agekey.enc", Some(&location), &never_scanned),
+            Err(StorageError::KeyNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_location_round_trips_through_json() {
+        let location = KeyFileLocation {
+            volume: Some(KeyVolume {
+                uuid: None,
+                label: "KEYS".to_string(),
+                relative_path: PathBuf::from("keys/laptop.agekey.enc"),
+            }),
+            last_known_path: PathBuf::from("/Volumes/KEYS/keys/laptop.agekey.enc"),
+        };
+
+        let json = serde_json::to_string(&location).unwrap();
+        assert!(!json.contains("uuid"));
+        assert_eq!(
+            serde_json::from_str::<KeyFileLocation>(&json).unwrap(),
+            location
+        );
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_unescape_mount_field() {
+        assert_eq!(
+            unescape_mount_field("/media/me/KEYS\\0401"),
+            "/media/me/KEYS 1"
+        );
+        assert_eq!(unescape_mount_field("/mnt/a\\134b"), "/mnt/a\\b");
+        assert_eq!(unescape_mount_field("/mnt/plain"), "/mnt/plain");
+    }
+}
@@ -0,0 +1,358 @@
+//! Legacy profile readers
+//!
+//! Deserializers for what older builds wrote, kept only so the bootstrap
+//! migration can read them. Nothing here writes; reading a profile yields
+//! current `KeyEntry` values plus the legacy files they came from.
+//!
+//! Two layouts are recognized, both under `<profile>/keys/`:
+//! - `<label>.agekey.meta` beside each `<label>.agekey.enc` (pre-registry)
+//! - `key-registry.json` with schema `barqly.vault.registry/1`
+
+use super::registry_persistence::KeyEntry;
+use crate::error::StorageError;
+use crate::services::key_management::shared::domain::models::key_lifecycle::{
+    KeyLifecycleStatus, StatusHistoryEntry,
+};
+use crate::services::key_management::shared::domain::models::legacy_migration::LegacyLayout;
+use crate::services::shared::infrastructure::sanitize_label;
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tracing::warn;
+
+/// Registry file name used by the first registry builds
+pub const LEGACY_REGISTRY_FILENAME: &str = "key-registry.json";
+
+/// Only schema the early registry layout ever wrote
+pub const LEGACY_REGISTRY_SCHEMA: &str = "barqly.vault.registry/1";
+
+/// Appended to legacy files once their contents were migrated
+pub const MIGRATED_SUFFIX: &str = ".migrated";
+
+const KEY_FILE_SUFFIX: &str = ".agekey.enc";
+const KEY_METADATA_SUFFIX: &str = ".agekey.meta";
+
+/// Pre-registry `.agekey.meta` contents
+///
+/// `passphrase_hint` was also written but is deliberately dropped.
+#[derive(Debug, Deserialize)]
+struct LegacyKeyMetadata {
+    label: String,
+    created_at: DateTime<Utc>,
+    /// Absolute path at the time; only the file name is still meaningful
+    file_path: PathBuf,
+    public_key: Option<String>,
+    #[serde(default)]
+    last_accessed: Option<DateTime<Utc>>,
+}
+
+/// `key-registry.json` from the first registry builds
+#[derive(Debug, Deserialize)]
+struct LegacyRegistry {
+    schema: String,
+    keys: HashMap<String, LegacyRegistryEntry>,
+}
+
+/// Registry entry before lifecycle tracking and YubiKey model names
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+enum LegacyRegistryEntry {
+    Passphrase {
+        label: String,
+        created_at: DateTime<Utc>,
+        last_used: Option<DateTime<Utc>>,
+        public_key: String,
+        key_filename: String,
+    },
+    Yubikey {
+        label: String,
+        created_at: DateTime<Utc>,
+        last_used: Option<DateTime<Utc>>,
+        serial: String,
+        slot: u8,
+        piv_slot: u8,
+        recipient: String,
+        identity_tag: String,
+        #[serde(default)]
+        firmware_version: Option<String>,
+        recovery_code_hash: String,
+    },
+}
+
+/// A key read from a legacy profile, converted to the current schema
+#[derive(Debug, Clone)]
+pub struct LegacyKey {
+    pub key_id: String,
+    pub entry: KeyEntry,
+    /// Encrypted key file to copy into the keys directory (passphrase keys)
+    pub key_file: Option<PathBuf>,
+}
+
+/// Everything read from one legacy profile
+#[derive(Debug, Clone, Default)]
+pub struct LegacyContents {
+    pub keys: Vec<LegacyKey>,
+    /// Files to rename with `MIGRATED_SUFFIX` once the keys are migrated
+    pub consumed: Vec<PathBuf>,
+}
+
+/// A profile directory holding data in a legacy layout
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LegacyProfile {
+    pub root: PathBuf,
+    pub layout: LegacyLayout,
+}
+
+impl LegacyProfile {
+    /// Recognize a legacy layout under `root`
+    ///
+    /// Files already renamed with `MIGRATED_SUFFIX` don't count, so a
+    /// migrated profile is no longer detected.
+    pub fn detect(root: &Path) -> Option<Self> {
+        let keys_dir = root.join("keys");
+        let layout = if keys_dir.join(LEGACY_REGISTRY_FILENAME).is_file() {
+            LegacyLayout::EarlyRegistry
+        } else if !files_with_suffix(&keys_dir, KEY_METADATA_SUFFIX).is_empty() {
+            LegacyLayout::KeyMetadataFiles
+        } else {
+            return None;
+        };
+
+        Some(Self {
+            root: root.to_path_buf(),
+            layout,
+        })
+    }
+
+    fn keys_dir(&self) -> PathBuf {
+        self.root.join("keys")
+    }
+
+    /// Read the profile's keys in the current schema
+    pub fn read(&self) -> Result<LegacyContents, StorageError> {
+        match self.layout {
+            LegacyLayout::KeyMetadataFiles => self.read_metadata_files(),
+            LegacyLayout::EarlyRegistry => self.read_early_registry(),
+        }
+    }
+
+    /// Unreadable or incomplete `.meta` files are skipped and left in place
+    fn read_metadata_files(&self) -> Result<LegacyContents, StorageError> {
+        let keys_dir = self.keys_dir();
+        let mut contents = LegacyContents::default();
+
+        for meta_path in files_with_suffix(&keys_dir, KEY_METADATA_SUFFIX) {
+            let metadata = match read_json::<LegacyKeyMetadata>(&meta_path) {
+                Ok(metadata) => metadata,
+                Err(e) => {
+                    warn!(path = %meta_path.display(), error = %e, "Skipping unreadable legacy key metadata");
+                    continue;
+                }
+            };
+            let Some(public_key) = metadata.public_key else {
+                warn!(path = %meta_path.display(), "Skipping legacy key metadata without a public key");
+                continue;
+            };
+            let key_id = match sanitize_label(&metadata.label) {
+                Ok(sanitized) => sanitized.sanitized,
+                Err(e) => {
+                    warn!(path = %meta_path.display(), error = %e, "Skipping legacy key with an unusable label");
+                    continue;
+                }
+            };
+
+            let key_filename = metadata
+                .file_path
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_else(|| format!("{}{}", key_id, KEY_FILE_SUFFIX));
+            let key_file = keys_dir.join(&key_filename);
+            if !key_file.is_file() {
+                warn!(path = %key_file.display(), "Skipping legacy key whose key file is missing");
+                continue;
+            }
+
+            contents.keys.push(LegacyKey {
+                key_id,
+                entry: KeyEntry::Passphrase {
+                    label: metadata.label,
+                    created_at: metadata.created_at,
+                    last_used: metadata.last_accessed,
+                    public_key,
+                    key_filename,
+                    lifecycle_status: initial_status(metadata.last_accessed),
+                    status_history: vec![migrated_history(metadata.last_accessed)],
+                    vault_associations: vec![],
+                    deactivated_at: None,
+                    previous_lifecycle_status: None,
+                    key_location: None,
+                    passphrase_policy: 0,
+                },
+                key_file: Some(key_file.clone()),
+            });
+            contents.consumed.push(meta_path);
+            contents.consumed.push(key_file);
+        }
+
+        Ok(contents)
+    }
+
+    fn read_early_registry(&self) -> Result<LegacyContents, StorageError> {
+        let keys_dir = self.keys_dir();
+        let registry_path = keys_dir.join(LEGACY_REGISTRY_FILENAME);
+        let registry = read_json::<LegacyRegistry>(&registry_path)?;
+        if registry.schema != LEGACY_REGISTRY_SCHEMA {
+            return Err(StorageError::InvalidFormat {
+                path: registry_path,
+                message: format!("Unsupported legacy registry schema '{}'", registry.schema),
+            });
+        }
+
+        let mut contents = LegacyContents::default();
+        let mut keys: Vec<_> = registry.keys.into_iter().collect();
+        keys.sort_by(|a, b| a.0.cmp(&b.0));
+
+        for (key_id, entry) in keys {
+            let (entry, key_file) = match entry {
+                LegacyRegistryEntry::Passphrase {
+                    label,
+                    created_at,
+                    last_used,
+                    public_key,
+                    key_filename,
+                } => {
+                    if !is_plain_file_name(&key_filename) {
+                        warn!(key_id = %key_id, "Skipping legacy key with an unsafe key file name");
+                        continue;
+                    }
+                    let key_file = keys_dir.join(&key_filename);
+                    if !key_file.is_file() {
+                        warn!(key_id = %key_id, path = %key_file.display(), "Legacy passphrase key file is missing");
+                    }
+                    let entry = KeyEntry::Passphrase {
+                        label,
+                        created_at,
+                        last_used,
+                        public_key,
+                        key_filename,
+                        lifecycle_status: initial_status(last_used),
+                        status_history: vec![migrated_history(last_used)],
+                        vault_associations: vec![],
+                        deactivated_at: None,
+                        previous_lifecycle_status: None,
+                        key_location: None,
+                        passphrase_policy: 0,
+                    };
+                    (entry, key_file.is_file().then_some(key_file))
+                }
+                LegacyRegistryEntry::Yubikey {
+                    label,
+                    created_at,
+                    last_used,
+                    serial,
+                    slot,
+                    piv_slot,
+                    recipient,
+                    identity_tag,
+                    firmware_version,
+                    recovery_code_hash,
+                } => {
+                    let entry = KeyEntry::Yubikey {
+                        label,
+                        created_at,
+                        last_used,
+                        serial,
+                        slot,
+                        piv_slot,
+                        recipient,
+                        identity_tag,
+                        model: "YubiKey".to_string(),
+                        firmware_version,
+                        recovery_code_hash,
+                        lifecycle_status: initial_status(last_used),
+                        status_history: vec![migrated_history(last_used)],
+                        vault_associations: vec![],
+                        deactivated_at: None,
+                        previous_lifecycle_status: None,
+                        last_reader: None,
+                    };
+                    (entry, None)
+                }
+            };
+
+            contents.consumed.extend(key_file.clone());
+            contents.keys.push(LegacyKey {
+                key_id,
+                entry,
+                key_file,
+            });
+        }
+
+        contents.consumed.push(registry_path);
+        Ok(contents)
+    }
+}
+
+/// Path a consumed legacy file is renamed to
+pub fn migrated_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(MIGRATED_SUFFIX);
+    path.with_file_name(name)
+}
+
+/// Legacy layouts never tracked vault attachments, so a used key starts
+/// suspended until a manifest merge attaches it, as in the v1 → v2 upgrade
+fn initial_status(last_used: Option<DateTime<Utc>>) -> KeyLifecycleStatus {
+    if last_used.is_some() {
+        KeyLifecycleStatus::Suspended
+    } else {
+        KeyLifecycleStatus::PreActivation
+    }
+}
+
+fn migrated_history(last_used: Option<DateTime<Utc>>) -> StatusHistoryEntry {
+    StatusHistoryEntry::new(
+        initial_status(last_used),
+        "Migrated from legacy profile",
+        "system",
+    )
+}
+
+/// Key file names must stay inside the keys directory
+fn is_plain_file_name(name: &str) -> bool {
+    Path::new(name)
+        .file_name()
+        .is_some_and(|file_name| file_name == name)
+}
+
+fn read_json<T: serde::de::DeserializeOwned>(path: &Path) -> Result<T, StorageError> {
+    let content = fs::read_to_string(path).map_err(|e| StorageError::FileReadFailed {
+        path: path.to_path_buf(),
+        source: e,
+    })?;
+    serde_json::from_str(&content).map_err(|e| StorageError::InvalidFormat {
+        path: path.to_path_buf(),
+        message: e.to_string(),
+    })
+}
+
+/// Files in `dir` whose names end with `suffix`, sorted by path
+fn files_with_suffix(dir: &Path, suffix: &str) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut files: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.is_file()
+                && path
+                    .file_name()
+                    .is_some_and(|name| name.to_string_lossy().ends_with(suffix))
+        })
+        .collect();
+    files.sort();
+    files
+}
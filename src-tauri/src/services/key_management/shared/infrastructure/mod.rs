@@ -2,11 +2,22 @@
 //!
 //! Contains technical implementations for key registry persistence and related operations.
 
+pub mod key_media;
 pub mod key_storage;
+pub mod legacy;
 pub mod registry_persistence;
+pub mod registry_repository;
 
 // Re-export key types for backward compatibility and convenience
-pub use registry_persistence::{KeyEntry, KeyRegistry, generate_recovery_code};
+pub use registry_persistence::{
+    KeyEntry, KeyRegistry, LabelConflict, LabelRename, RecoveryShareConfig, generate_recovery_code,
+};
+
+// Re-export the in-memory registry
+pub use registry_repository::RegistryRepository;
+
+// Re-export key file resolution for keys kept on removable media
+pub use key_media::{SystemVolumes, VolumeSource, load_passphrase_key_file, resolve_key_file};
 
 // Re-export key storage functions (replacing storage::key_store)
 pub use key_storage::{
@@ -3,16 +3,22 @@
 //! Centralizes management of all encryption keys (passphrase and YubiKey) in a single registry.
 //! This replaces the previous scattered approach of individual .meta files and separate manifests.
 
+use crate::services::key_management::shared::domain::models::contact::{Contact, ContactError};
 use crate::services::key_management::shared::domain::models::key_lifecycle::{
     KeyLifecycleStatus, StatusHistoryEntry,
 };
-use crate::services::shared::infrastructure::io::atomic_write_sync;
+use crate::services::key_management::shared::domain::models::key_location::KeyFileLocation;
+use crate::services::key_management::shared::infrastructure::key_media::{
+    SystemVolumes, resolve_key_file,
+};
+use crate::services::key_management::shared::infrastructure::registry_repository::RegistryRepository;
+use crate::services::shared::infrastructure::io::PendingWrite;
 use crate::services::shared::infrastructure::path_management::get_keys_dir;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use tracing::{debug, info, warn};
 
 /// Unified key entry that can represent any type of encryption key
@@ -44,6 +50,16 @@ pub enum KeyEntry {
         #[serde(default)]
         #[serde(skip_serializing_if = "Option::is_none")]
         previous_lifecycle_status: Option<KeyLifecycleStatus>,
+
+        // Set once the key file is relinked outside the keys directory
+        #[serde(default)]
+        #[serde(skip_serializing_if = "Option::is_none")]
+        key_location: Option<KeyFileLocation>,
+
+        // Passphrase normalization policy the key file is protected under;
+        // 0 for key files from before normalization
+        #[serde(default)]
+        passphrase_policy: u32,
     },
     /// YubiKey hardware token
     #[serde(rename = "yubikey")]
@@ -76,6 +92,12 @@ pub enum KeyEntry {
         #[serde(default)]
         #[serde(skip_serializing_if = "Option::is_none")]
         previous_lifecycle_status: Option<KeyLifecycleStatus>,
+
+        // Smart-card reader the key was last used on, the default reader
+        // hint when several readers are attached
+        #[serde(default)]
+        #[serde(skip_serializing_if = "Option::is_none")]
+        last_reader: Option<String>,
     },
     /// Recipient - public key only entry for encrypting to others
     /// Unlike Passphrase/YubiKey, the user does NOT have the private key.
@@ -104,6 +126,53 @@ pub enum KeyEntry {
         #[serde(skip_serializing_if = "Option::is_none")]
         previous_lifecycle_status: Option<KeyLifecycleStatus>,
     },
+    /// Reserved YubiKey slot - a placeholder for hardware not registered yet
+    /// Has no recipient, so it never takes part in encryption. Registering a
+    /// YubiKey against it converts the entry in place.
+    #[serde(rename = "yubikey_pending")]
+    YubikeyPending {
+        label: String,
+        created_at: DateTime<Utc>,
+
+        // NIST lifecycle fields
+        #[serde(default = "default_lifecycle_status")]
+        lifecycle_status: KeyLifecycleStatus,
+        #[serde(default)]
+        status_history: Vec<StatusHistoryEntry>,
+        #[serde(default)]
+        vault_associations: Vec<String>,
+
+        // Deactivation tracking
+        #[serde(default)]
+        #[serde(skip_serializing_if = "Option::is_none")]
+        deactivated_at: Option<DateTime<Utc>>,
+
+        #[serde(default)]
+        #[serde(skip_serializing_if = "Option::is_none")]
+        previous_lifecycle_status: Option<KeyLifecycleStatus>,
+    },
+}
+
+/// A label is already used by another key in the registry
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("Label '{label}' is already used by key '{existing_key_id}'")]
+pub struct LabelConflict {
+    pub label: String,
+    pub existing_key_id: String,
+}
+
+/// A label changed by `KeyRegistry::normalize_labels`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+pub struct LabelRename {
+    pub key_id: String,
+    pub old_label: String,
+    pub new_label: String,
+}
+
+/// Labels are compared trimmed and case-insensitively: key IDs and filenames
+/// derive from the label, and those collide on case-insensitive filesystems
+fn labels_match(a: &str, b: &str) -> bool {
+    a.trim().to_lowercase() == b.trim().to_lowercase()
 }
 
 // Default function for lifecycle_status to ensure backward compatibility
@@ -118,6 +187,17 @@ pub fn label(&self) -> &str {
             KeyEntry::Passphrase { label, .. } => label,
             KeyEntry::Yubikey { label, .. } => label,
             KeyEntry::Recipient { label, .. } => label,
+            KeyEntry::YubikeyPending { label, .. } => label,
+        }
+    }
+
+    /// Replace the display label
+    pub fn set_label(&mut self, new_label: String) {
+        match self {
+            KeyEntry::Passphrase { label, .. } => *label = new_label,
+            KeyEntry::Yubikey { label, .. } => *label = new_label,
+            KeyEntry::Recipient { label, .. } => *label = new_label,
+            KeyEntry::YubikeyPending { label, .. } => *label = new_label,
         }
     }
 
@@ -127,6 +207,7 @@ pub fn created_at(&self) -> DateTime<Utc> {
             KeyEntry::Passphrase { created_at, .. } => *created_at,
             KeyEntry::Yubikey { created_at, .. } => *created_at,
             KeyEntry::Recipient { created_at, .. } => *created_at,
+            KeyEntry::YubikeyPending { created_at, .. } => *created_at,
         }
     }
 
@@ -136,6 +217,7 @@ pub fn last_used(&self) -> Option<DateTime<Utc>> {
             KeyEntry::Passphrase { last_used, .. } => *last_used,
             KeyEntry::Yubikey { last_used, .. } => *last_used,
             KeyEntry::Recipient { last_used, .. } => *last_used,
+            KeyEntry::YubikeyPending { .. } => None,
         }
     }
 
@@ -146,6 +228,7 @@ pub fn update_last_used(&mut self) {
             KeyEntry::Passphrase { last_used, .. } => *last_used = Some(now),
             KeyEntry::Yubikey { last_used, .. } => *last_used = Some(now),
             KeyEntry::Recipient { last_used, .. } => *last_used = Some(now),
+            KeyEntry::YubikeyPending { .. } => {}
         }
     }
 
@@ -164,6 +247,11 @@ pub fn is_recipient(&self) -> bool {
         matches!(self, KeyEntry::Recipient { .. })
     }
 
+    /// Check if this is a reserved YubiKey slot awaiting its hardware
+    pub fn is_yubikey_pending(&self) -> bool {
+        matches!(self, KeyEntry::YubikeyPending { .. })
+    }
+
     /// Check if this is an owned key (user has private key)
     /// Returns true for Passphrase and YubiKey, false for Recipient
     pub fn is_owned_key(&self) -> bool {
@@ -178,6 +266,14 @@ pub fn yubikey_serial(&self) -> Option<&str> {
         }
     }
 
+    /// Get the reader a YubiKey was last used on
+    pub fn last_reader(&self) -> Option<&str> {
+        match self {
+            KeyEntry::Yubikey { last_reader, .. } => last_reader.as_deref(),
+            _ => None,
+        }
+    }
+
     /// Get passphrase key filename if this is a passphrase entry
     pub fn passphrase_filename(&self) -> Option<&str> {
         match self {
@@ -186,12 +282,32 @@ pub fn passphrase_filename(&self) -> Option<&str> {
         }
     }
 
+    /// Get where a relinked passphrase key file lives
+    pub fn key_location(&self) -> Option<&KeyFileLocation> {
+        match self {
+            KeyEntry::Passphrase { key_location, .. } => key_location.as_ref(),
+            _ => None,
+        }
+    }
+
+    /// Get the passphrase normalization policy of a passphrase key
+    pub fn passphrase_policy(&self) -> Option<u32> {
+        match self {
+            KeyEntry::Passphrase {
+                passphrase_policy, ..
+            } => Some(*passphrase_policy),
+            _ => None,
+        }
+    }
+
     /// Get the public key/recipient string for encryption
     pub fn public_key(&self) -> &str {
         match self {
             KeyEntry::Passphrase { public_key, .. } => public_key,
             KeyEntry::Yubikey { recipient, .. } => recipient,
             KeyEntry::Recipient { public_key, .. } => public_key,
+            // No recipient until the hardware is registered
+            KeyEntry::YubikeyPending { .. } => "",
         }
     }
 
@@ -207,6 +323,9 @@ pub fn lifecycle_status(&self) -> KeyLifecycleStatus {
             KeyEntry::Recipient {
                 lifecycle_status, ..
             } => *lifecycle_status,
+            KeyEntry::YubikeyPending {
+                lifecycle_status, ..
+            } => *lifecycle_status,
         }
     }
 
@@ -256,6 +375,14 @@ pub fn set_lifecycle_status(
                 *lifecycle_status = status;
                 status_history.push(history_entry);
             }
+            KeyEntry::YubikeyPending {
+                lifecycle_status,
+                status_history,
+                ..
+            } => {
+                *lifecycle_status = status;
+                status_history.push(history_entry);
+            }
         }
 
         Ok(())
@@ -267,6 +394,7 @@ pub fn status_history(&self) -> &[StatusHistoryEntry] {
             KeyEntry::Passphrase { status_history, .. } => status_history,
             KeyEntry::Yubikey { status_history, .. } => status_history,
             KeyEntry::Recipient { status_history, .. } => status_history,
+            KeyEntry::YubikeyPending { status_history, .. } => status_history,
         }
     }
 
@@ -282,6 +410,9 @@ pub fn vault_associations(&self) -> &[String] {
             KeyEntry::Recipient {
                 vault_associations, ..
             } => vault_associations,
+            KeyEntry::YubikeyPending {
+                vault_associations, ..
+            } => vault_associations,
         }
     }
 
@@ -309,6 +440,13 @@ pub fn add_vault_association(&mut self, vault_id: String) {
                     vault_associations.push(vault_id);
                 }
             }
+            KeyEntry::YubikeyPending {
+                vault_associations, ..
+            } => {
+                if !vault_associations.contains(&vault_id) {
+                    vault_associations.push(vault_id);
+                }
+            }
         }
     }
 
@@ -330,6 +468,11 @@ pub fn remove_vault_association(&mut self, vault_id: &str) {
             } => {
                 vault_associations.retain(|id| id != vault_id);
             }
+            KeyEntry::YubikeyPending {
+                vault_associations, ..
+            } => {
+                vault_associations.retain(|id| id != vault_id);
+            }
         }
     }
 
@@ -402,6 +545,22 @@ pub fn deactivate(&mut self, reason: String, changed_by: String) -> Result<(), S
                     changed_by,
                 ));
             }
+            KeyEntry::YubikeyPending {
+                lifecycle_status,
+                status_history,
+                deactivated_at,
+                previous_lifecycle_status,
+                ..
+            } => {
+                *previous_lifecycle_status = Some(*lifecycle_status);
+                *lifecycle_status = KeyLifecycleStatus::Deactivated;
+                *deactivated_at = Some(Utc::now());
+                status_history.push(StatusHistoryEntry::new(
+                    KeyLifecycleStatus::Deactivated,
+                    reason,
+                    changed_by,
+                ));
+            }
         }
 
         Ok(())
@@ -490,6 +649,31 @@ pub fn restore(&mut self, reason: String, changed_by: String) -> Result<(), Stri
                     }
                 };
 
+                *lifecycle_status = restore_to;
+                *deactivated_at = None;
+                *previous_lifecycle_status = None;
+                status_history.push(StatusHistoryEntry::new(restore_to, reason, changed_by));
+            }
+            KeyEntry::YubikeyPending {
+                lifecycle_status,
+                status_history,
+                deactivated_at,
+                previous_lifecycle_status,
+                vault_associations,
+                ..
+            } => {
+                // Determine the state to restore to
+                let restore_to = if let Some(prev_status) = previous_lifecycle_status {
+                    *prev_status
+                } else {
+                    // Fallback: restore based on vault associations
+                    if !vault_associations.is_empty() {
+                        KeyLifecycleStatus::Active
+                    } else {
+                        KeyLifecycleStatus::Suspended
+                    }
+                };
+
                 *lifecycle_status = restore_to;
                 *deactivated_at = None;
                 *previous_lifecycle_status = None;
@@ -506,9 +690,77 @@ pub fn deactivated_at(&self) -> Option<DateTime<Utc>> {
             KeyEntry::Passphrase { deactivated_at, .. } => *deactivated_at,
             KeyEntry::Yubikey { deactivated_at, .. } => *deactivated_at,
             KeyEntry::Recipient { deactivated_at, .. } => *deactivated_at,
+            KeyEntry::YubikeyPending { deactivated_at, .. } => *deactivated_at,
         }
     }
 
+    /// Revoke a lost key, recording the key that replaces it
+    ///
+    /// Vault associations are kept: a vault still listing a revoked key has
+    /// not been moved to the replacement yet.
+    pub fn revoke(
+        &mut self,
+        replaced_by: &str,
+        reason: String,
+        changed_by: String,
+    ) -> Result<(), String> {
+        let current_status = self.lifecycle_status();
+        if !current_status.can_transition_to(KeyLifecycleStatus::Revoked) {
+            return Err(format!(
+                "Cannot revoke key in {:?} state. Only Active or Suspended keys can be revoked.",
+                current_status
+            ));
+        }
+
+        let history_entry = StatusHistoryEntry::with_metadata(
+            KeyLifecycleStatus::Revoked,
+            reason,
+            changed_by,
+            serde_json::json!({ "replaced_by": replaced_by }),
+        );
+        match self {
+            KeyEntry::Passphrase {
+                lifecycle_status,
+                status_history,
+                ..
+            }
+            | KeyEntry::Yubikey {
+                lifecycle_status,
+                status_history,
+                ..
+            }
+            | KeyEntry::Recipient {
+                lifecycle_status,
+                status_history,
+                ..
+            }
+            | KeyEntry::YubikeyPending {
+                lifecycle_status,
+                status_history,
+                ..
+            } => {
+                *lifecycle_status = KeyLifecycleStatus::Revoked;
+                status_history.push(history_entry);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Key that replaced this one, if it has been revoked
+    pub fn replaced_by(&self) -> Option<&str> {
+        if self.lifecycle_status() != KeyLifecycleStatus::Revoked {
+            return None;
+        }
+        self.status_history()
+            .iter()
+            .rev()
+            .find(|entry| entry.status == KeyLifecycleStatus::Revoked)
+            .and_then(|entry| entry.metadata.as_ref())
+            .and_then(|metadata| metadata.get("replaced_by"))
+            .and_then(|value| value.as_str())
+    }
+
     /// Destroy the key immediately (permanent deletion)
     /// This transitions the key to Destroyed state without the 30-day grace period.
     /// Can be called on PreActivation, Active, Suspended, or Deactivated keys.
@@ -580,18 +832,58 @@ pub fn destroy(&mut self, reason: String, changed_by: String) -> Result<(), Stri
                     changed_by,
                 ));
             }
+            KeyEntry::YubikeyPending {
+                lifecycle_status,
+                status_history,
+                deactivated_at,
+                previous_lifecycle_status,
+                ..
+            } => {
+                *lifecycle_status = KeyLifecycleStatus::Destroyed;
+                // Clear deactivation metadata since we're bypassing the grace period
+                *deactivated_at = None;
+                *previous_lifecycle_status = None;
+                status_history.push(StatusHistoryEntry::new(
+                    KeyLifecycleStatus::Destroyed,
+                    reason,
+                    changed_by,
+                ));
+            }
         }
 
         Ok(())
     }
 }
 
+/// Shamir recovery configuration for a vault
+///
+/// Only the split parameters and a hash of the secret are recorded; shares
+/// are shown to the user once and never persisted.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecoveryShareConfig {
+    pub threshold: u8,
+    pub total: u8,
+    /// SHA-256 of the recovery secret, used to verify reconstruction
+    pub verification_hash: String,
+    /// Public key of the recovery identity added as a vault recipient
+    pub public_key: String,
+    /// Encrypted recovery identity file (passphrase is the recovery secret)
+    pub key_filename: String,
+    pub created_at: DateTime<Utc>,
+}
+
 /// Central registry for all encryption keys
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct KeyRegistry {
     pub schema: String, // "barqly.vault.registry/1"
     /// Map of key_id -> KeyEntry
     pub keys: HashMap<String, KeyEntry>,
+    /// Map of vault_id -> recovery share configuration
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub recovery_shares: HashMap<String, RecoveryShareConfig>,
+    /// Map of contact_id -> other people's recipients (encrypt-only)
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub contacts: HashMap<String, Contact>,
 }
 
 impl Default for KeyRegistry {
@@ -606,19 +898,27 @@ pub fn new() -> Self {
         Self {
             schema: "barqly.vault.registry/2".to_string(), // Bumped for NIST lifecycle
             keys: HashMap::new(),
+            recovery_shares: HashMap::new(),
+            contacts: HashMap::new(),
         }
     }
 
-    /// Load registry from disk, creating new if it doesn't exist
+    /// Load the registry, creating new if it doesn't exist
+    ///
+    /// Served from memory while the file is unchanged (see
+    /// `RegistryRepository`).
     pub fn load() -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
-        let path = Self::get_registry_path()?;
+        RegistryRepository::for_path(&Self::get_registry_path()?).read()
+    }
 
+    /// Load the registry stored at `path`, creating new if it doesn't exist
+    pub fn load_from(path: &Path) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
         if !path.exists() {
             debug!("Key registry doesn't exist, creating new one");
             return Ok(Self::new());
         }
 
-        let content = fs::read_to_string(&path)?;
+        let content = fs::read_to_string(path)?;
         let mut registry: Self = serde_json::from_str(&content)?;
 
         // Migrate from v1 to v2 if needed
@@ -637,35 +937,31 @@ pub fn load() -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
         Ok(registry)
     }
 
-    /// Save registry to disk
+    /// Save registry to disk and update the copy in memory
     pub fn save(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        let path = Self::get_registry_path()?;
+        RegistryRepository::for_path(&Self::get_registry_path()?).write(self)
+    }
 
-        // Pretty print for readability
+    /// Serialized registry as a write for a journaled mutation
+    pub fn to_pending_write(
+        &self,
+    ) -> Result<PendingWrite, Box<dyn std::error::Error + Send + Sync>> {
+        let path = Self::get_registry_path()?;
         let json = serde_json::to_string_pretty(self)?;
-
-        // Atomic write to prevent corruption if process crashes mid-write
-        atomic_write_sync(&path, json.as_bytes())?;
-
-        // Set restrictive permissions on Unix
-        #[cfg(unix)]
-        {
-            use std::os::unix::fs::PermissionsExt;
-            let permissions = fs::Permissions::from_mode(0o600);
-            fs::set_permissions(&path, permissions)?;
-        }
-
-        debug!(key_count = self.keys.len(), "Saved key registry to disk");
-
-        Ok(())
+        Ok(PendingWrite::new(path, json.into_bytes()))
     }
 
     /// Register a new key in the registry
+    ///
+    /// Rejects both duplicate key IDs and labels already used by another key.
     pub fn register_key(&mut self, key_id: String, entry: KeyEntry) -> Result<(), String> {
         if self.keys.contains_key(&key_id) {
             return Err(format!("Key with ID '{}' already exists", key_id));
         }
 
+        self.check_label_available(entry.label(), None)
+            .map_err(|e| e.to_string())?;
+
         info!("Registering new key");
         debug!(
             key_id = %key_id,
@@ -683,6 +979,9 @@ pub fn update_key(&mut self, key_id: &str, entry: KeyEntry) -> Result<(), String
             return Err(format!("Key with ID '{}' not found", key_id));
         }
 
+        self.check_label_change(key_id, entry.label())
+            .map_err(|e| e.to_string())?;
+
         debug!(
             key_id = %key_id,
             label = entry.label(),
@@ -729,12 +1028,113 @@ pub fn add_yubikey_entry(
             vault_associations: vec![],
             deactivated_at: None,
             previous_lifecycle_status: None,
+            last_reader: None,
         };
 
         self.keys.insert(key_id.clone(), entry);
         key_id
     }
 
+    /// Reserve a YubiKey slot in a vault before the hardware is at hand
+    ///
+    /// The placeholder holds the label and vault until
+    /// `fulfill_yubikey_reservation` turns it into the registered YubiKey.
+    pub fn reserve_yubikey_slot(
+        &mut self,
+        key_id: String,
+        label: String,
+        vault_id: String,
+    ) -> Result<(), String> {
+        let entry = KeyEntry::YubikeyPending {
+            label,
+            created_at: Utc::now(),
+            lifecycle_status: KeyLifecycleStatus::PreActivation,
+            status_history: vec![StatusHistoryEntry::new(
+                KeyLifecycleStatus::PreActivation,
+                "YubiKey slot reserved",
+                "user",
+            )],
+            vault_associations: vec![vault_id],
+            deactivated_at: None,
+            previous_lifecycle_status: None,
+        };
+        self.register_key(key_id, entry)
+    }
+
+    /// Convert a reserved slot into the YubiKey registered for it
+    ///
+    /// The key ID, label, creation date, vault associations and history
+    /// carry over; the device fields come from the registration.
+    #[allow(clippy::too_many_arguments)]
+    pub fn fulfill_yubikey_reservation(
+        &mut self,
+        key_id: &str,
+        serial: String,
+        slot: u8,
+        piv_slot: u8,
+        recipient: String,
+        identity_tag: String,
+        model: String,
+        firmware_version: Option<String>,
+        recovery_code_hash: String,
+    ) -> Result<(), String> {
+        let Some(KeyEntry::YubikeyPending {
+            label,
+            created_at,
+            lifecycle_status,
+            mut status_history,
+            vault_associations,
+            deactivated_at,
+            previous_lifecycle_status,
+        }) = self.keys.get(key_id).cloned()
+        else {
+            return Err(format!("Key '{}' is not a reserved YubiKey slot", key_id));
+        };
+
+        status_history.push(StatusHistoryEntry::new(
+            lifecycle_status,
+            "Reservation fulfilled by a registered YubiKey",
+            "user",
+        ));
+        let entry = KeyEntry::Yubikey {
+            label,
+            created_at,
+            last_used: None,
+            serial,
+            slot,
+            piv_slot,
+            recipient,
+            identity_tag,
+            model,
+            firmware_version,
+            recovery_code_hash,
+            lifecycle_status,
+            status_history,
+            vault_associations,
+            deactivated_at,
+            previous_lifecycle_status,
+            last_reader: None,
+        };
+
+        info!(key_id, "Fulfilled YubiKey reservation");
+        self.keys.insert(key_id.to_string(), entry);
+        Ok(())
+    }
+
+    /// Reserved YubiKey slots waiting for hardware in a vault, oldest first
+    pub fn pending_yubikeys(&self, vault_id: &str) -> Vec<(&String, &KeyEntry)> {
+        let mut pending: Vec<_> = self
+            .keys
+            .iter()
+            .filter(|(_, entry)| {
+                entry.is_yubikey_pending()
+                    && entry.vault_associations().iter().any(|id| id == vault_id)
+            })
+            .collect();
+        pending.sort_by_key(|(id, entry)| (entry.created_at(), id.as_str()));
+        pending
+    }
+
     /// Remove a key from the registry
     pub fn remove_key(&mut self, key_id: &str) -> Result<KeyEntry, String> {
         self.keys
@@ -801,6 +1201,26 @@ pub fn find_yubikey_by_serial(&self, serial: &str) -> Option<(&String, &KeyEntry
             .find(|(_, entry)| entry.yubikey_serial() == Some(serial))
     }
 
+    /// Remember the reader a YubiKey was last used on
+    ///
+    /// Returns whether anything changed, so callers only save when needed.
+    pub fn set_last_reader(&mut self, serial: &str, reader_name: &str) -> bool {
+        let entry = self
+            .keys
+            .values_mut()
+            .find(|entry| entry.yubikey_serial() == Some(serial));
+
+        match entry {
+            Some(KeyEntry::Yubikey { last_reader, .. })
+                if last_reader.as_deref() != Some(reader_name) =>
+            {
+                *last_reader = Some(reader_name.to_string());
+                true
+            }
+            _ => false,
+        }
+    }
+
     /// Find entry by public key (for duplicate detection)
     pub fn find_by_public_key(&self, public_key: &str) -> Option<(&String, &KeyEntry)> {
         self.keys
@@ -808,6 +1228,182 @@ pub fn find_by_public_key(&self, public_key: &str) -> Option<(&String, &KeyEntry
             .find(|(_, entry)| entry.public_key() == public_key)
     }
 
+    /// Find the key using `label`, ignoring `except_key_id`
+    ///
+    /// With pre-existing duplicates the lowest key ID wins, so lookups are
+    /// deterministic.
+    pub fn find_by_label(
+        &self,
+        label: &str,
+        except_key_id: Option<&str>,
+    ) -> Option<(&String, &KeyEntry)> {
+        self.keys
+            .iter()
+            .filter(|(id, _)| Some(id.as_str()) != except_key_id)
+            .filter(|(_, entry)| labels_match(entry.label(), label))
+            .min_by_key(|(id, _)| id.as_str())
+    }
+
+    /// Fail if another key already uses `label`
+    pub fn check_label_available(
+        &self,
+        label: &str,
+        except_key_id: Option<&str>,
+    ) -> Result<(), LabelConflict> {
+        match self.find_by_label(label, except_key_id) {
+            Some((existing_key_id, _)) => Err(LabelConflict {
+                label: label.trim().to_string(),
+                existing_key_id: existing_key_id.clone(),
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// Fail if relabelling `key_id` to `new_label` would collide
+    ///
+    /// Leaving the label unchanged always succeeds, so entries that were
+    /// duplicated before uniqueness was enforced can still be updated.
+    pub fn check_label_change(&self, key_id: &str, new_label: &str) -> Result<(), LabelConflict> {
+        if self
+            .keys
+            .get(key_id)
+            .is_some_and(|entry| entry.label() == new_label)
+        {
+            return Ok(());
+        }
+        self.check_label_available(new_label, Some(key_id))
+    }
+
+    /// First free label among `label`, `label (2)`, `label (3)`, ...
+    pub fn next_available_label(&self, label: &str, except_key_id: Option<&str>) -> String {
+        let base = label.trim();
+        if self.find_by_label(base, except_key_id).is_none() {
+            return base.to_string();
+        }
+
+        (2..)
+            .map(|n| format!("{} ({})", base, n))
+            .find(|candidate| self.find_by_label(candidate, except_key_id).is_none())
+            .expect("unbounded suffix search always finds a free label")
+    }
+
+    /// Label to use for a new key: `label` itself when free, otherwise a
+    /// suffixed variant with `auto_rename`, or a conflict error without
+    pub fn resolve_label(&self, label: &str, auto_rename: bool) -> Result<String, LabelConflict> {
+        if auto_rename {
+            return Ok(self.next_available_label(label, None));
+        }
+        self.check_label_available(label, None)?;
+        Ok(label.trim().to_string())
+    }
+
+    /// Rename duplicated labels so every key has a unique one
+    ///
+    /// Within each group of duplicates the oldest key (by creation date, then
+    /// key ID) keeps the label and the others get `label (2)`, `label (3)`, ...
+    /// in the same order. Returns the renames performed.
+    pub fn normalize_labels(&mut self) -> Vec<LabelRename> {
+        let mut ordered: Vec<(String, DateTime<Utc>)> = self
+            .keys
+            .iter()
+            .map(|(id, entry)| (id.clone(), entry.created_at()))
+            .collect();
+        ordered.sort_by(|(a_id, a_created), (b_id, b_created)| {
+            a_created.cmp(b_created).then_with(|| a_id.cmp(b_id))
+        });
+
+        let mut renames = Vec::new();
+        let mut kept: Vec<String> = Vec::new();
+
+        for (key_id, _) in ordered {
+            let label = self.keys[&key_id].label().to_string();
+            if !kept.iter().any(|existing| labels_match(existing, &label)) {
+                kept.push(label);
+                continue;
+            }
+
+            let new_label = (2..)
+                .map(|n| format!("{} ({})", label.trim(), n))
+                .find(|candidate| {
+                    !kept
+                        .iter()
+                        .any(|existing| labels_match(existing, candidate))
+                        && self.find_by_label(candidate, Some(&key_id)).is_none()
+                })
+                .expect("unbounded suffix search always finds a free label");
+
+            warn!(
+                key_id = %key_id,
+                old_label = %label,
+                new_label = %new_label,
+                "Renaming duplicate key label"
+            );
+
+            if let Some(entry) = self.keys.get_mut(&key_id) {
+                entry.set_label(new_label.clone());
+            }
+            kept.push(new_label.clone());
+            renames.push(LabelRename {
+                key_id,
+                old_label: label,
+                new_label,
+            });
+        }
+
+        renames
+    }
+
+    /// Add a contact
+    ///
+    /// Refuses a recipient that is one of the user's own keys, or that is
+    /// already saved as a public-key-only key or another contact.
+    pub fn add_contact(
+        &mut self,
+        contact_id: String,
+        contact: Contact,
+    ) -> Result<(), ContactError> {
+        if let Some((_, entry)) = self.find_by_public_key(&contact.recipient) {
+            return Err(if entry.is_owned_key() {
+                ContactError::OwnKey(entry.label().to_string())
+            } else {
+                ContactError::DuplicateRecipient(entry.label().to_string())
+            });
+        }
+        if let Some(existing) = self
+            .contacts
+            .values()
+            .find(|existing| existing.recipient == contact.recipient)
+        {
+            return Err(ContactError::DuplicateRecipient(existing.name.clone()));
+        }
+
+        self.contacts.insert(contact_id, contact);
+        Ok(())
+    }
+
+    /// Remove a contact, returning it
+    pub fn remove_contact(&mut self, contact_id: &str) -> Result<Contact, ContactError> {
+        self.contacts
+            .remove(contact_id)
+            .ok_or_else(|| ContactError::NotFound(contact_id.to_string()))
+    }
+
+    /// Look up contacts by ID, in the order given; fails on the first unknown ID
+    pub fn resolve_contacts<'a>(
+        &'a self,
+        contact_ids: &'a [String],
+    ) -> Result<Vec<(&'a str, &'a Contact)>, ContactError> {
+        contact_ids
+            .iter()
+            .map(|id| {
+                self.contacts
+                    .get(id)
+                    .map(|contact| (id.as_str(), contact))
+                    .ok_or_else(|| ContactError::NotFound(id.clone()))
+            })
+            .collect()
+    }
+
     /// Mark a key as used (updates last_used timestamp)
     pub fn mark_key_used(&mut self, key_id: &str) -> Result<(), String> {
         let entry = self
@@ -829,29 +1425,53 @@ pub fn get_passphrase_key_path(
             .ok_or_else(|| format!("Key with ID '{}' not found", key_id))?;
 
         match entry {
-            KeyEntry::Passphrase { key_filename, .. } => {
-                let keys_dir = get_keys_dir()?;
-                Ok(keys_dir.join(key_filename))
-            }
+            KeyEntry::Passphrase {
+                key_filename,
+                key_location,
+                ..
+            } => Ok(resolve_key_file(
+                key_filename,
+                key_location.as_ref(),
+                &SystemVolumes,
+            )?),
             _ => Err(format!("Key '{}' is not a passphrase key", key_id).into()),
         }
     }
 
-    /// Get registry file path
-    fn get_registry_path() -> Result<PathBuf, Box<dyn std::error::Error + Send + Sync>> {
-        let keys_dir = get_keys_dir()?;
-        Ok(keys_dir.join("barqly-vault-key-registry.json"))
+    /// Point a passphrase key at a key file outside the keys directory
+    pub fn relink_key_file(
+        &mut self,
+        key_id: &str,
+        location: KeyFileLocation,
+    ) -> Result<(), String> {
+        match self.keys.get_mut(key_id) {
+            Some(KeyEntry::Passphrase { key_location, .. }) => {
+                *key_location = Some(location);
+                Ok(())
+            }
+            Some(_) => Err(format!("Key '{}' is not a passphrase key", key_id)),
+            None => Err(format!("Key with ID '{}' not found", key_id)),
+        }
     }
 
-    /// Migrate from existing systems (for migration support)
-    pub fn migrate_from_existing() -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
-        let registry = Self::new();
-
-        // This will be implemented as we remove the old systems
-        // For now, return empty registry
-        warn!("Migration from existing systems not yet implemented");
+    /// Record that a passphrase key file was re-protected under `policy`
+    pub fn set_passphrase_policy(&mut self, key_id: &str, policy: u32) -> Result<(), String> {
+        match self.keys.get_mut(key_id) {
+            Some(KeyEntry::Passphrase {
+                passphrase_policy, ..
+            }) => {
+                *passphrase_policy = policy;
+                Ok(())
+            }
+            Some(_) => Err(format!("Key '{}' is not a passphrase key", key_id)),
+            None => Err(format!("Key with ID '{}' not found", key_id)),
+        }
+    }
 
-        Ok(registry)
+    /// Get registry file path
+    pub fn get_registry_path() -> Result<PathBuf, Box<dyn std::error::Error + Send + Sync>> {
+        let keys_dir = get_keys_dir()?;
+        Ok(keys_dir.join("barqly-vault-key-registry.json"))
     }
 
     /// Migrate registry from v1 to v2 (add NIST lifecycle fields)
@@ -914,6 +1534,8 @@ fn migrate_to_v2(&mut self) -> Result<(), Box<dyn std::error::Error + Send + Syn
                         "system",
                     ));
                 }
+                // Reserved slots postdate v1 registries
+                KeyEntry::YubikeyPending { .. } => {}
             }
 
             debug!(
@@ -973,6 +1595,8 @@ fn create_test_registry() -> KeyRegistry {
             vault_associations: vec![],
             deactivated_at: None,
             previous_lifecycle_status: None,
+            key_location: None,
+            passphrase_policy: 0,
         };
         registry
             .register_key("keyref_test1".to_string(), passphrase_entry)
@@ -1000,6 +1624,7 @@ fn create_test_registry() -> KeyRegistry {
             vault_associations: vec![],
             deactivated_at: None,
             previous_lifecycle_status: None,
+            last_reader: None,
         };
         registry
             .register_key("keyref_test2".to_string(), yubikey_entry)
@@ -1045,6 +1670,24 @@ fn test_yubikey_serial_lookup() {
         assert!(not_found.is_none());
     }
 
+    #[test]
+    fn test_revoke_records_replacement() {
+        let mut registry = create_test_registry();
+        let key = registry.get_key_mut("keyref_test2").unwrap();
+        assert_eq!(key.replaced_by(), None);
+
+        key.revoke("keyref_new", "Lost".to_string(), "user".to_string())
+            .unwrap();
+        assert_eq!(key.lifecycle_status(), KeyLifecycleStatus::Revoked);
+        assert_eq!(key.replaced_by(), Some("keyref_new"));
+
+        // Revoking twice is not a valid transition
+        assert!(
+            key.revoke("keyref_other", "Lost".to_string(), "user".to_string())
+                .is_err()
+        );
+    }
+
     #[test]
     fn test_key_usage_tracking() {
         let mut registry = create_test_registry();
@@ -1060,4 +1703,351 @@ fn test_key_usage_tracking() {
         let key = registry.get_key("keyref_test1").unwrap();
         assert!(key.last_used().is_some());
     }
+
+    #[test]
+    fn test_relink_key_file_only_for_passphrase_keys() {
+        let mut registry = create_test_registry();
+        let location = KeyFileLocation {
+            volume: None,
+            last_known_path: PathBuf::from("/srv/keys/test.agekey.enc"),
+        };
+
+        registry
+            .relink_key_file("keyref_test1", location.clone())
+            .unwrap();
+        assert_eq!(
+            registry.get_key("keyref_test1").unwrap().key_location(),
+            Some(&location)
+        );
+
+        assert!(
+            registry
+                .relink_key_file("keyref_test2", location.clone())
+                .is_err()
+        );
+        assert!(registry.relink_key_file("missing", location).is_err());
+    }
+
+    fn recipient_entry(label: &str, public_key: &str, created_at: DateTime<Utc>) -> KeyEntry {
+        KeyEntry::Recipient {
+            label: label.to_string(),
+            created_at,
+            last_used: None,
+            public_key: public_key.to_string(),
+            lifecycle_status: KeyLifecycleStatus::Active,
+            status_history: vec![],
+            vault_associations: vec![],
+            deactivated_at: None,
+            previous_lifecycle_status: None,
+        }
+    }
+
+    #[test]
+    fn test_register_rejects_duplicate_label() {
+        let mut registry = create_test_registry();
+
+        let result = registry.register_key(
+            "keyref_test3".to_string(),
+            recipient_entry(" test passphrase ", "age1other", Utc::now()),
+        );
+
+        assert!(result.unwrap_err().contains("keyref_test1"));
+        assert!(!registry.contains_key("keyref_test3"));
+    }
+
+    #[test]
+    fn test_resolve_label_conflict_without_auto_rename() {
+        let registry = create_test_registry();
+
+        let conflict = registry.resolve_label("Test YubiKey", false).unwrap_err();
+        assert_eq!(conflict.existing_key_id, "keyref_test2");
+
+        assert_eq!(
+            registry.resolve_label("Fresh Label", false).unwrap(),
+            "Fresh Label"
+        );
+    }
+
+    #[test]
+    fn test_resolve_label_auto_rename_picks_next_suffix() {
+        let mut registry = create_test_registry();
+        registry
+            .register_key(
+                "keyref_test3".to_string(),
+                recipient_entry("Test YubiKey (2)", "age1other", Utc::now()),
+            )
+            .unwrap();
+
+        assert_eq!(
+            registry.resolve_label("Test YubiKey", true).unwrap(),
+            "Test YubiKey (3)"
+        );
+    }
+
+    #[test]
+    fn test_label_change_checks_other_keys_only() {
+        let registry = create_test_registry();
+
+        assert!(
+            registry
+                .check_label_change("keyref_test1", "Test Passphrase")
+                .is_ok()
+        );
+        assert!(
+            registry
+                .check_label_change("keyref_test1", "Renamed")
+                .is_ok()
+        );
+
+        let conflict = registry
+            .check_label_change("keyref_test1", "test yubikey")
+            .unwrap_err();
+        assert_eq!(conflict.existing_key_id, "keyref_test2");
+    }
+
+    #[test]
+    fn test_update_key_rejects_label_collision() {
+        let mut registry = create_test_registry();
+        let mut entry = registry.get_key("keyref_test1").unwrap().clone();
+        entry.set_label("Test YubiKey".to_string());
+
+        assert!(registry.update_key("keyref_test1", entry).is_err());
+        assert_eq!(
+            registry.get_key("keyref_test1").unwrap().label(),
+            "Test Passphrase"
+        );
+    }
+
+    #[test]
+    fn test_set_last_reader_by_serial() {
+        let mut registry = create_test_registry();
+        let reader = "Yubico YubiKey OTP+FIDO+CCID";
+
+        assert!(registry.set_last_reader("12345678", reader));
+        assert!(!registry.set_last_reader("12345678", reader));
+        assert!(!registry.set_last_reader("87654321", reader));
+
+        let (_, entry) = registry.find_yubikey_by_serial("12345678").unwrap();
+        assert_eq!(entry.last_reader(), Some(reader));
+    }
+
+    #[test]
+    fn test_normalize_labels_renames_by_creation_date() {
+        let mut registry = KeyRegistry::new();
+        let base = Utc::now();
+        // Inserted directly: duplicates predating label enforcement
+        registry.keys.insert(
+            "key_c".to_string(),
+            recipient_entry("My Key", "age1c", base + chrono::Duration::days(2)),
+        );
+        registry.keys.insert(
+            "key_a".to_string(),
+            recipient_entry("My Key", "age1a", base),
+        );
+        registry.keys.insert(
+            "key_b".to_string(),
+            recipient_entry("my key", "age1b", base + chrono::Duration::days(1)),
+        );
+        registry
+            .keys
+            .insert("key_d".to_string(), recipient_entry("Other", "age1d", base));
+
+        let renames = registry.normalize_labels();
+
+        assert_eq!(
+            renames,
+            vec![
+                LabelRename {
+                    key_id: "key_b".to_string(),
+                    old_label: "my key".to_string(),
+                    new_label: "my key (2)".to_string(),
+                },
+                LabelRename {
+                    key_id: "key_c".to_string(),
+                    old_label: "My Key".to_string(),
+                    new_label: "My Key (3)".to_string(),
+                },
+            ]
+        );
+        assert_eq!(registry.get_key("key_a").unwrap().label(), "My Key");
+        assert_eq!(registry.get_key("key_d").unwrap().label(), "Other");
+
+        // Idempotent once labels are unique
+        assert!(registry.normalize_labels().is_empty());
+    }
+
+    fn contact(name: &str, recipient: &str) -> Contact {
+        Contact {
+            name: name.to_string(),
+            recipient: recipient.to_string(),
+            note: None,
+            added_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_contact_crud() {
+        let mut registry = create_test_registry();
+
+        registry
+            .add_contact("contact-sam-1".to_string(), contact("Sam", "age1sam"))
+            .unwrap();
+        let ids = vec!["contact-sam-1".to_string()];
+        let resolved = registry.resolve_contacts(&ids).unwrap();
+        assert_eq!(resolved[0].1.name, "Sam");
+
+        let missing = vec!["contact-nobody-1".to_string()];
+        assert!(matches!(
+            registry.resolve_contacts(&missing),
+            Err(ContactError::NotFound(_))
+        ));
+
+        assert_eq!(
+            registry.remove_contact("contact-sam-1").unwrap().name,
+            "Sam"
+        );
+        assert!(registry.contacts.is_empty());
+        assert!(matches!(
+            registry.remove_contact("contact-sam-1"),
+            Err(ContactError::NotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_contact_rejects_own_and_duplicate_recipients() {
+        let mut registry = create_test_registry();
+        registry
+            .register_key(
+                "keyref_test3".to_string(),
+                recipient_entry("Alex", "age1alex", Utc::now()),
+            )
+            .unwrap();
+        registry
+            .add_contact("contact-sam-1".to_string(), contact("Sam", "age1sam"))
+            .unwrap();
+
+        assert!(matches!(
+            registry.add_contact("contact-me-1".to_string(), contact("Me", "age1test123...")),
+            Err(ContactError::OwnKey(label)) if label == "Test Passphrase"
+        ));
+        assert!(matches!(
+            registry.add_contact("contact-alex-1".to_string(), contact("Alex", "age1alex")),
+            Err(ContactError::DuplicateRecipient(label)) if label == "Alex"
+        ));
+        assert!(matches!(
+            registry.add_contact("contact-sam-2".to_string(), contact("Samuel", "age1sam")),
+            Err(ContactError::DuplicateRecipient(name)) if name == "Sam"
+        ));
+        assert_eq!(registry.contacts.len(), 1);
+    }
+
+    fn fulfill(registry: &mut KeyRegistry, key_id: &str) -> Result<(), String> {
+        registry.fulfill_yubikey_reservation(
+            key_id,
+            "87654321".to_string(),
+            1,
+            82,
+            "age1yubikey1reserved".to_string(),
+            "AGE-PLUGIN-YUBIKEY-RESERVED".to_string(),
+            "YubiKey 5C".to_string(),
+            Some("5.7.1".to_string()),
+            "hash".to_string(),
+        )
+    }
+
+    #[test]
+    fn test_reserve_yubikey_slot() {
+        let mut registry = create_test_registry();
+        registry
+            .reserve_yubikey_slot(
+                "Backup-YubiKey".to_string(),
+                "Backup YubiKey".to_string(),
+                "vault-001".to_string(),
+            )
+            .unwrap();
+
+        let entry = registry.get_key("Backup-YubiKey").unwrap();
+        assert!(entry.is_yubikey_pending());
+        assert!(!entry.is_owned_key());
+        assert_eq!(entry.public_key(), "");
+        assert_eq!(entry.lifecycle_status(), KeyLifecycleStatus::PreActivation);
+        assert_eq!(registry.pending_yubikeys("vault-001").len(), 1);
+        assert!(registry.pending_yubikeys("vault-002").is_empty());
+
+        // Survives a save and reload
+        let json = serde_json::to_string(&registry).unwrap();
+        assert!(json.contains(r#""type":"yubikey_pending""#));
+        let loaded: KeyRegistry = serde_json::from_str(&json).unwrap();
+        assert!(
+            loaded
+                .get_key("Backup-YubiKey")
+                .unwrap()
+                .is_yubikey_pending()
+        );
+
+        // Labels stay unique across reservations
+        assert!(
+            registry
+                .reserve_yubikey_slot(
+                    "Test-YubiKey".to_string(),
+                    "test yubikey".to_string(),
+                    "vault-001".to_string(),
+                )
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_fulfill_yubikey_reservation_converts_in_place() {
+        let mut registry = create_test_registry();
+        registry
+            .reserve_yubikey_slot(
+                "Backup-YubiKey".to_string(),
+                "Backup YubiKey".to_string(),
+                "vault-001".to_string(),
+            )
+            .unwrap();
+        let reserved_at = registry.get_key("Backup-YubiKey").unwrap().created_at();
+
+        fulfill(&mut registry, "Backup-YubiKey").unwrap();
+
+        let entry = registry.get_key("Backup-YubiKey").unwrap();
+        assert!(entry.is_yubikey());
+        assert_eq!(entry.label(), "Backup YubiKey");
+        assert_eq!(entry.created_at(), reserved_at);
+        assert_eq!(entry.yubikey_serial(), Some("87654321"));
+        assert_eq!(entry.public_key(), "age1yubikey1reserved");
+        assert_eq!(entry.vault_associations(), &["vault-001".to_string()]);
+        assert_eq!(entry.status_history().len(), 2);
+        assert!(registry.pending_yubikeys("vault-001").is_empty());
+
+        // Only reservations can be fulfilled, and only once
+        assert!(fulfill(&mut registry, "Backup-YubiKey").is_err());
+        assert!(fulfill(&mut registry, "keyref_test1").is_err());
+        assert!(fulfill(&mut registry, "missing").is_err());
+    }
+
+    #[test]
+    fn test_unfulfilled_reservation_can_be_deleted() {
+        let mut registry = create_test_registry();
+        registry
+            .reserve_yubikey_slot(
+                "Backup-YubiKey".to_string(),
+                "Backup YubiKey".to_string(),
+                "vault-001".to_string(),
+            )
+            .unwrap();
+
+        let entry = registry.remove_key("Backup-YubiKey").unwrap();
+        assert!(entry.is_yubikey_pending());
+        assert!(registry.pending_yubikeys("vault-001").is_empty());
+        assert!(fulfill(&mut registry, "Backup-YubiKey").is_err());
+
+        // The label is free for the hardware registered later
+        assert!(
+            registry
+                .check_label_available("Backup YubiKey", None)
+                .is_ok()
+        );
+    }
 }
@@ -0,0 +1,322 @@
+//! In-memory key registry
+//!
+//! Commands that touch keys fire in bursts, and each used to re-read and
+//! re-parse the registry file. The repository loads the registry once per
+//! file and serves every reader a copy from memory. Writes persist through
+//! the atomic write path while holding the write lock, and only a write that
+//! reached the disk replaces the copy in memory, so readers see either the
+//! registry before a write or after it, never a mix.
+//!
+//! Each read compares the file's modification time, size and inode with the
+//! copy in memory and reloads when another process (the CLI, a second
+//! instance) or a journaled mutation has replaced the file. A file that
+//! doesn't parse, e.g. one still being written on a filesystem without
+//! atomic renames, leaves the copy in memory in use.
+
+use super::registry_persistence::KeyRegistry;
+use crate::services::shared::infrastructure::RegistryRevisions;
+use crate::services::shared::infrastructure::io::atomic_write_sync;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock, RwLock};
+use std::time::SystemTime;
+use tracing::{debug, warn};
+
+type RepositoryResult<T> = Result<T, Box<dyn std::error::Error + Send + Sync>>;
+
+/// What the registry file looked like when it was last read or written
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct FileStamp {
+    modified: Option<SystemTime>,
+    len: u64,
+    inode: u64,
+}
+
+impl FileStamp {
+    /// Stamp of the file at `path`, `None` if there's no file
+    fn of(path: &Path) -> Option<Self> {
+        let metadata = fs::metadata(path).ok()?;
+        #[cfg(unix)]
+        let inode = std::os::unix::fs::MetadataExt::ino(&metadata);
+        #[cfg(not(unix))]
+        let inode = 0;
+        Some(Self {
+            modified: metadata.modified().ok(),
+            len: metadata.len(),
+            inode,
+        })
+    }
+}
+
+#[derive(Debug)]
+struct Cached {
+    registry: KeyRegistry,
+    stamp: Option<FileStamp>,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    cached: Option<Cached>,
+}
+
+/// The key registry stored at one path, held in memory
+#[derive(Debug)]
+pub struct RegistryRepository {
+    path: PathBuf,
+    state: RwLock<State>,
+    revisions: &'static RegistryRevisions,
+}
+
+impl RegistryRepository {
+    /// Repository for the registry at `path`, not yet loaded
+    pub fn new(path: PathBuf) -> Self {
+        Self::with_revisions(path, RegistryRevisions::global())
+    }
+
+    /// Repository counting its changes in `revisions` instead of the app's
+    pub fn with_revisions(path: PathBuf, revisions: &'static RegistryRevisions) -> Self {
+        Self {
+            path,
+            state: RwLock::new(State::default()),
+            revisions,
+        }
+    }
+
+    /// The process-wide repository for the registry at `path`
+    pub fn for_path(path: &Path) -> Arc<Self> {
+        static REPOSITORIES: OnceLock<Mutex<HashMap<PathBuf, Arc<RegistryRepository>>>> =
+            OnceLock::new();
+        let mut repositories = REPOSITORIES
+            .get_or_init(Default::default)
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        repositories
+            .entry(path.to_path_buf())
+            .or_insert_with(|| Arc::new(Self::new(path.to_path_buf())))
+            .clone()
+    }
+
+    /// Revision of the registry, as reported to the UI
+    ///
+    /// Every write and reload advances `change_events::registry_revision`,
+    /// so this is the revision list commands and change events carry.
+    pub fn revision(&self) -> u64 {
+        self.revisions.current()
+    }
+
+    /// A copy of the registry, reloaded first if the file has changed
+    pub fn read(&self) -> RepositoryResult<KeyRegistry> {
+        let current = FileStamp::of(&self.path);
+        {
+            let state = self.state.read().unwrap_or_else(|e| e.into_inner());
+            if let Some(cached) = &state.cached
+                && cached.stamp == current
+            {
+                return Ok(cached.registry.clone());
+            }
+        }
+
+        let mut state = self.state.write().unwrap_or_else(|e| e.into_inner());
+        // Another reader may have reloaded while we waited
+        let current = FileStamp::of(&self.path);
+        if let Some(cached) = &state.cached
+            && cached.stamp == current
+        {
+            return Ok(cached.registry.clone());
+        }
+
+        match KeyRegistry::load_from(&self.path) {
+            Ok(registry) => {
+                debug!(
+                    reloaded = state.cached.is_some(),
+                    "Key registry loaded into memory"
+                );
+                state.cached = Some(Cached {
+                    registry: registry.clone(),
+                    stamp: current,
+                });
+                self.revisions.record_write();
+                Ok(registry)
+            }
+            Err(e) => match &state.cached {
+                Some(cached) => {
+                    warn!(error = %e, "Key registry file unreadable; keeping the copy in memory");
+                    Ok(cached.registry.clone())
+                }
+                None => Err(e),
+            },
+        }
+    }
+
+    /// Persist `registry` and make it the copy in memory
+    ///
+    /// Readers wait until the file is written. If the write fails, the copy
+    /// in memory is left as it was.
+    pub fn write(&self, registry: &KeyRegistry) -> RepositoryResult<()> {
+        let json = serde_json::to_string_pretty(registry)?;
+
+        let mut state = self.state.write().unwrap_or_else(|e| e.into_inner());
+        // Atomic write to prevent corruption if process crashes mid-write
+        atomic_write_sync(&self.path, json.as_bytes())?;
+
+        // Set restrictive permissions on Unix
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let permissions = fs::Permissions::from_mode(0o600);
+            fs::set_permissions(&self.path, permissions)?;
+        }
+
+        state.cached = Some(Cached {
+            registry: registry.clone(),
+            stamp: FileStamp::of(&self.path),
+        });
+        let revision = self.revisions.record_write();
+        debug!(
+            key_count = registry.keys.len(),
+            revision, "Saved key registry"
+        );
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::key_management::shared::{KeyEntry, KeyLifecycleStatus};
+    use chrono::Utc;
+    use std::thread;
+    use tempfile::TempDir;
+
+    /// Repository for `path` with revisions of its own, counting from zero
+    fn repository_at(path: PathBuf) -> RegistryRepository {
+        let revisions = Box::leak(Box::new(RegistryRevisions::new()));
+        RegistryRepository::with_revisions(path, revisions)
+    }
+
+    /// Registry whose every contact key carries `generation` in its label
+    fn registry_at(generation: usize, keys: usize) -> KeyRegistry {
+        let mut registry = KeyRegistry::new();
+        for i in 0..keys {
+            registry.keys.insert(
+                format!("key_{i}"),
+                KeyEntry::Recipient {
+                    label: format!("gen{generation} key{i}"),
+                    created_at: Utc::now(),
+                    last_used: None,
+                    public_key: format!("age1test{i}"),
+                    lifecycle_status: KeyLifecycleStatus::Active,
+                    status_history: vec![],
+                    vault_associations: vec![],
+                    deactivated_at: None,
+                    previous_lifecycle_status: None,
+                },
+            );
+        }
+        registry
+    }
+
+    fn generations(registry: &KeyRegistry) -> Vec<String> {
+        let mut generations: Vec<String> = registry
+            .keys
+            .values()
+            .map(|entry| entry.label().split(' ').next().unwrap().to_string())
+            .collect();
+        generations.sort();
+        generations.dedup();
+        generations
+    }
+
+    #[test]
+    fn test_readers_never_see_a_partial_write() {
+        let temp = TempDir::new().unwrap();
+        let repository = Arc::new(RegistryRepository::new(temp.path().join("registry.json")));
+        repository.write(&registry_at(0, 20)).unwrap();
+
+        let readers: Vec<_> = (0..4)
+            .map(|_| {
+                let repository = Arc::clone(&repository);
+                thread::spawn(move || {
+                    for _ in 0..200 {
+                        let registry = repository.read().unwrap();
+                        assert_eq!(registry.keys.len(), 20);
+                        assert_eq!(generations(&registry).len(), 1);
+                    }
+                })
+            })
+            .collect();
+        for generation in 1..=20 {
+            repository.write(&registry_at(generation, 20)).unwrap();
+        }
+        for reader in readers {
+            reader.join().unwrap();
+        }
+
+        assert_eq!(generations(&repository.read().unwrap()), vec!["gen20"]);
+    }
+
+    #[test]
+    fn test_reloads_after_external_change() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("registry.json");
+        let repository = repository_at(path.clone());
+        repository.write(&registry_at(0, 1)).unwrap();
+        assert_eq!(repository.read().unwrap().keys.len(), 1);
+        assert_eq!(repository.revision(), 1);
+
+        // Another process replaces the file
+        let json = serde_json::to_string_pretty(&registry_at(1, 3)).unwrap();
+        fs::write(&path, json).unwrap();
+        assert_eq!(repository.read().unwrap().keys.len(), 3);
+        assert_eq!(repository.revision(), 2);
+
+        // A half-written file keeps the copy in memory
+        fs::write(&path, b"{\"schema\": \"barqly.vault.reg").unwrap();
+        assert_eq!(repository.read().unwrap().keys.len(), 3);
+        assert_eq!(repository.revision(), 2);
+    }
+
+    #[test]
+    fn test_unchanged_file_is_served_from_memory() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("registry.json");
+        fs::write(
+            &path,
+            serde_json::to_string_pretty(&registry_at(0, 2)).unwrap(),
+        )
+        .unwrap();
+        let repository = repository_at(path);
+
+        // First read loads the file
+        assert_eq!(repository.read().unwrap().keys.len(), 2);
+        assert_eq!(repository.revision(), 1);
+
+        // Unchanged file: served from memory, no reload
+        for _ in 0..3 {
+            assert_eq!(repository.read().unwrap().keys.len(), 2);
+        }
+        assert_eq!(repository.revision(), 1);
+    }
+
+    #[test]
+    fn test_failed_write_keeps_memory_unchanged() {
+        let temp = TempDir::new().unwrap();
+        let dir = temp.path().join("keys");
+        fs::create_dir(&dir).unwrap();
+        let repository = repository_at(dir.join("registry.json"));
+        repository.write(&registry_at(0, 2)).unwrap();
+        assert_eq!(repository.revision(), 1);
+
+        // The keys directory is gone and a file stands in its place
+        fs::remove_dir_all(&dir).unwrap();
+        fs::write(&dir, b"").unwrap();
+        assert!(repository.write(&registry_at(1, 5)).is_err());
+
+        let state = repository.state.read().unwrap();
+        let cached = state.cached.as_ref().unwrap();
+        assert_eq!(cached.registry.keys.len(), 2);
+        assert_eq!(generations(&cached.registry), vec!["gen0"]);
+        assert_eq!(repository.revision(), 1);
+    }
+}
@@ -17,13 +17,15 @@
 
 // Re-export key registry infrastructure types
 pub use infrastructure::{
-    KeyEntry, KeyInfo, KeyRegistry, delete_key, generate_recovery_code, get_key_info, key_exists,
-    list_keys, load_encrypted_key, save_encrypted_key, save_encrypted_key_with_metadata,
-    save_yubikey_metadata,
+    KeyEntry, KeyInfo, KeyRegistry, LabelConflict, LabelRename, RecoveryShareConfig, delete_key,
+    generate_recovery_code, get_key_info, key_exists, list_keys, load_encrypted_key,
+    save_encrypted_key, save_encrypted_key_with_metadata, save_yubikey_metadata,
 };
 
 // Re-export application layer services and manager
-pub use application::{KeyManagementError, KeyManager, KeyRegistryService, UnifiedKeyListService};
+pub use application::{
+    ContactService, KeyManagementError, KeyManager, KeyRegistryService, UnifiedKeyListService,
+};
 
 // Re-export domain types
 pub use domain::models::key_lifecycle::KeyLifecycleStatus;
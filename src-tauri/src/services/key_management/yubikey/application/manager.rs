@@ -12,12 +12,17 @@
 //! - **State Management**: Manages YubiKey states and transitions
 //! - **Event Publishing**: Publishes events for UI updates and logging
 
+use crate::logging::spawn_blocking;
 use crate::prelude::*;
 use crate::services::key_management::yubikey::{
+    application::pin_guard::{PinAttemptGuard, read_counter},
     application::services::ServiceFactory,
     domain::errors::{YubiKeyError, YubiKeyResult},
-    domain::models::{Pin, Serial, YubiKeyDevice, YubiKeyIdentity},
+    domain::models::{Pin, PluginProtocolInfo, Serial, YubiKeyDevice, YubiKeyIdentity},
+    infrastructure::age::{AgePluginProvider, plugin_protocol},
+    infrastructure::pty::{PtySessionInfo, PtySessionRegistry},
 };
+use std::sync::Arc;
 
 /// YubiKey Manager - Main facade for all YubiKey operations
 ///
@@ -29,6 +34,8 @@ pub struct YubiKeyManager {
     services: ServiceFactory,
     /// Current configuration
     config: YubiKeyManagerConfig,
+    /// Shared across managers so a failed PIN is remembered between commands
+    pin_guard: Arc<PinAttemptGuard>,
 }
 
 /// Configuration for YubiKey Manager
@@ -42,6 +49,9 @@ pub struct YubiKeyManagerConfig {
     pub auto_detect_devices: bool,
     /// Enable event publishing
     pub enable_events: bool,
+    /// Smart-card reader to detect devices on; defaults to the reader each
+    /// YubiKey was last used on
+    pub reader_hint: Option<String>,
 }
 
 impl Default for YubiKeyManagerConfig {
@@ -51,6 +61,7 @@ fn default() -> Self {
             operation_timeout_secs: 30,
             auto_detect_devices: true,
             enable_events: true,
+            reader_hint: None,
         }
     }
 }
@@ -68,7 +79,7 @@ pub async fn with_config(config: YubiKeyManagerConfig) -> YubiKeyResult<Self> {
         let services = ServiceFactory::new().await?;
         services.initialize_all_services().await?;
 
-        let manager = Self { services, config };
+        let manager = Self::with_services(services, config);
 
         debug!("YubiKey Manager initialized successfully");
         Ok(manager)
@@ -76,7 +87,17 @@ pub async fn with_config(config: YubiKeyManagerConfig) -> YubiKeyResult<Self> {
 
     /// Create YubiKey manager with custom services (for testing)
     pub fn with_services(services: ServiceFactory, config: YubiKeyManagerConfig) -> Self {
-        Self { services, config }
+        Self {
+            services,
+            config,
+            pin_guard: PinAttemptGuard::global(),
+        }
+    }
+
+    /// Use `pin_guard` instead of the process-wide guard (for testing)
+    pub fn with_pin_guard(mut self, pin_guard: Arc<PinAttemptGuard>) -> Self {
+        self.pin_guard = pin_guard;
+        self
     }
 
     // =============================================================================
@@ -287,6 +308,7 @@ pub async fn list_yubikeys_with_state(
                 has_tdes_protected_mgmt_key: has_tdes_mgmt_key,
                 created_at,
                 last_used,
+                reader_name: device.reader_name.clone(),
             };
 
             info!(
@@ -303,10 +325,42 @@ pub async fn list_yubikeys_with_state(
     }
 
     /// Detect specific YubiKey device by serial
-    pub async fn detect_device(&self, serial: &Serial) -> YubiKeyResult<Option<YubiKeyDevice>> {
+    ///
+    /// An explicit `reader_hint` (or one from the config) is strict: a
+    /// different key on that reader is a `ReaderSerialMismatch` error. Without
+    /// one, the reader the key was last used on is tried first, falling back
+    /// to every reader if it's gone or now holds another key. The reader the
+    /// device was found on is remembered for next time.
+    pub async fn detect_device(
+        &self,
+        serial: &Serial,
+        reader_hint: Option<&str>,
+    ) -> YubiKeyResult<Option<YubiKeyDevice>> {
         debug!("Detecting YubiKey device: {}", serial.redacted());
 
-        self.services.device_service().detect_device(serial).await
+        let device_service = self.services.device_service();
+        let explicit_hint = reader_hint.or(self.config.reader_hint.as_deref());
+        let device = match (explicit_hint, last_reader(serial)) {
+            (Some(hint), _) => device_service.detect_device(serial, Some(hint)).await?,
+            (None, Some(remembered)) => {
+                match device_service
+                    .detect_device(serial, Some(&remembered))
+                    .await
+                {
+                    Ok(device) => device,
+                    Err(e) => {
+                        debug!("Remembered reader not usable ({}), checking all readers", e);
+                        device_service.detect_device(serial, None).await?
+                    }
+                }
+            }
+            (None, None) => device_service.detect_device(serial, None).await?,
+        };
+
+        if let Some(reader) = device.as_ref().and_then(|d| d.reader_name.as_deref()) {
+            remember_reader(serial, reader);
+        }
+        Ok(device)
     }
 
     /// Check if YubiKey device is connected
@@ -327,6 +381,47 @@ pub async fn validate_pin(&self, serial: &Serial, pin: &Pin) -> YubiKeyResult<bo
             .await
     }
 
+    /// PIN attempts left before the YubiKey blocks its PIN
+    pub async fn get_pin_retries(&self, serial: &Serial) -> YubiKeyResult<u8> {
+        self.services.device_service().get_pin_retries(serial).await
+    }
+
+    /// Run a PIN-consuming `attempt` under the PIN guard
+    ///
+    /// Refuses to send the PIN when it's blocked, when `pin` failed on this
+    /// device moments ago, or when only one attempt is left and
+    /// `accept_last_attempt` isn't set. Wrong PINs are remembered for the
+    /// debounce.
+    pub async fn guard_pin_attempt<T, E, F>(
+        &self,
+        serial: &Serial,
+        pin: &Pin,
+        accept_last_attempt: bool,
+        attempt: F,
+    ) -> Result<T, E>
+    where
+        F: std::future::Future<Output = Result<T, E>>,
+        E: From<YubiKeyError>,
+    {
+        let retries_before = read_counter(serial, self.get_pin_retries(serial).await);
+        self.pin_guard
+            .check(serial, pin.value(), retries_before, accept_last_attempt)?;
+
+        let result = attempt.await;
+        let retries_after = match &result {
+            Ok(_) => None,
+            Err(_) => read_counter(serial, self.get_pin_retries(serial).await),
+        };
+        self.pin_guard.record(
+            serial,
+            pin.value(),
+            result.is_ok(),
+            retries_before,
+            retries_after,
+        );
+        result
+    }
+
     /// Check if YubiKey has default PIN (123456)
     pub async fn has_default_pin(&self, serial: &Serial) -> YubiKeyResult<bool> {
         debug!("Checking default PIN for YubiKey: {}", serial.redacted());
@@ -529,7 +624,7 @@ pub async fn initialize_device_hardware(
         use crate::services::key_management::yubikey::infrastructure::pty::ykman_ops::initialize_yubikey;
 
         // Initialize hardware with user-provided PINs
-        tokio::task::spawn_blocking({
+        spawn_blocking({
             let serial_value = serial.value().to_string();
             let pin_value = pin.value().to_string();
             let recovery_pin_value = recovery_pin.value().to_string();
@@ -557,7 +652,7 @@ pub async fn initialize_device(
 
         // 1. Detect device
         let device = self
-            .detect_device(serial)
+            .detect_device(serial, None)
             .await?
             .ok_or_else(|| YubiKeyError::device_not_found(serial))?;
 
@@ -614,7 +709,7 @@ pub async fn validate_device(
 
         // 1. Check device connection
         let device = self
-            .detect_device(serial)
+            .detect_device(serial, None)
             .await?
             .ok_or_else(|| YubiKeyError::device_not_found(serial))?;
 
@@ -663,6 +758,32 @@ pub async fn get_service_metrics(
         self.services.get_all_service_metrics().await
     }
 
+    // =============================================================================
+    // PTY Session Diagnostics
+    // =============================================================================
+    //
+    // These don't touch the device or services, so they work even when a wedged
+    // session would make full manager initialization hang.
+
+    /// List active PTY sessions (age, age-plugin-yubikey, ykman)
+    pub fn pty_sessions() -> Vec<PtySessionInfo> {
+        PtySessionRegistry::global().list()
+    }
+
+    /// Advertised age-plugin-yubikey version and the plugin protocol it
+    /// negotiates with our age library
+    pub async fn plugin_protocol_info() -> YubiKeyResult<PluginProtocolInfo> {
+        let plugin_path = AgePluginProvider::find_plugin_binary()?;
+        Ok(plugin_protocol::plugin_protocol_info(&plugin_path).await)
+    }
+
+    /// Kill a PTY session and remove its temporary files
+    ///
+    /// Returns false if no session with this ID is registered.
+    pub fn kill_pty_session(session_id: &str) -> bool {
+        PtySessionRegistry::global().kill(session_id).is_ok()
+    }
+
     /// Shutdown manager and all services gracefully
     pub async fn shutdown(&self) -> YubiKeyResult<()> {
         debug!("Shutting down YubiKey Manager");
@@ -688,6 +809,39 @@ pub fn services(&self) -> &ServiceFactory {
 unsafe impl Send for YubiKeyManager {}
 unsafe impl Sync for YubiKeyManager {}
 
+/// Reader the YubiKey with `serial` was last used on
+fn last_reader(serial: &Serial) -> Option<String> {
+    use crate::services::key_management::shared::infrastructure::registry_persistence::KeyRegistry;
+
+    let registry = KeyRegistry::load().ok()?;
+    let (_, entry) = registry.find_yubikey_by_serial(serial.value())?;
+    entry.last_reader().map(str::to_string)
+}
+
+/// Remember the reader a registered YubiKey was found on
+///
+/// Only a convenience for the next detection, so failures are just logged.
+fn remember_reader(serial: &Serial, reader_name: &str) {
+    use crate::services::key_management::shared::infrastructure::registry_persistence::KeyRegistry;
+
+    let mut registry = match KeyRegistry::load() {
+        Ok(registry) => registry,
+        Err(e) => {
+            warn!("Failed to load registry to remember reader: {}", e);
+            return;
+        }
+    };
+    if registry.set_last_reader(serial.value(), reader_name)
+        && let Err(e) = registry.save()
+    {
+        warn!(
+            "Failed to remember reader for YubiKey {}: {}",
+            serial.redacted(),
+            e
+        );
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -700,6 +854,7 @@ async fn test_manager_creation() {
         assert_eq!(config.operation_timeout_secs, 30);
         assert!(config.auto_detect_devices);
         assert!(config.enable_events);
+        assert!(config.reader_hint.is_none());
     }
 
     #[test]
@@ -709,12 +864,14 @@ fn test_manager_config() {
             operation_timeout_secs: 60,
             auto_detect_devices: false,
             enable_events: false,
+            reader_hint: Some("Yubico YubiKey".to_string()),
         };
 
         assert_eq!(config.max_pin_attempts, 5);
         assert_eq!(config.operation_timeout_secs, 60);
         assert!(!config.auto_detect_devices);
         assert!(!config.enable_events);
+        assert!(config.reader_hint.is_some());
     }
 
     #[test]
@@ -6,6 +6,7 @@
 pub mod events;
 pub mod factory;
 pub mod manager;
+pub mod pin_guard;
 pub mod services;
 pub mod state;
 
@@ -13,4 +14,5 @@
 pub use events::*;
 pub use factory::YubiKeyFactory;
 pub use manager::{YubiKeyManager, YubiKeyManagerConfig};
+pub use pin_guard::{PIN_RETRY_DEBOUNCE, PinAttemptGuard, read_counter};
 pub use state::*;
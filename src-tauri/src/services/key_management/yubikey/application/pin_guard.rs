@@ -0,0 +1,228 @@
+//! PIN attempt guard
+//!
+//! The PIV applet blocks its PIN after three wrong tries, and only the PUK
+//! gets it back. Before a PIN is sent to a YubiKey the guard checks the
+//! device's retry counter: a blocked PIN isn't tried at all, and the last
+//! attempt is only spent when the caller explicitly accepts it. A PIN that
+//! just failed on a device isn't sent again within a short window, so a
+//! repeated submit can't burn through the remaining tries.
+//!
+//! Failed PINs are remembered as salted hashes, in memory only.
+
+use crate::prelude::*;
+use crate::services::key_management::yubikey::domain::errors::{YubiKeyError, YubiKeyResult};
+use crate::services::key_management::yubikey::domain::models::Serial;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+/// How long the same PIN is refused after it failed on a device
+pub const PIN_RETRY_DEBOUNCE: Duration = Duration::from_secs(10);
+
+/// The last wrong PIN tried on a device
+struct FailedAttempt {
+    pin_hash: [u8; 32],
+    at: Instant,
+}
+
+/// Guards PIN-consuming operations against locking the PIN
+pub struct PinAttemptGuard {
+    debounce: Duration,
+    salt: [u8; 32],
+    failures: Mutex<HashMap<String, FailedAttempt>>,
+}
+
+impl std::fmt::Debug for PinAttemptGuard {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PinAttemptGuard")
+            .field("debounce", &self.debounce)
+            .finish_non_exhaustive()
+    }
+}
+
+impl PinAttemptGuard {
+    pub fn new() -> Self {
+        Self::with_debounce(PIN_RETRY_DEBOUNCE)
+    }
+
+    pub fn with_debounce(debounce: Duration) -> Self {
+        Self {
+            debounce,
+            salt: rand::random(),
+            failures: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// The guard shared by every YubiKey operation in this process
+    pub fn global() -> Arc<PinAttemptGuard> {
+        static GUARD: OnceLock<Arc<PinAttemptGuard>> = OnceLock::new();
+        GUARD.get_or_init(|| Arc::new(Self::new())).clone()
+    }
+
+    /// Refuse to send `pin` if it would block the PIN or just failed
+    ///
+    /// `retries` is the device's counter, `None` if it couldn't be read.
+    pub fn check(
+        &self,
+        serial: &Serial,
+        pin: &str,
+        retries: Option<u8>,
+        accept_last_attempt: bool,
+    ) -> YubiKeyResult<()> {
+        if retries == Some(0) {
+            return Err(YubiKeyError::pin_blocked(serial));
+        }
+
+        let pin_hash = self.hash(serial, pin);
+        let failures = self.failures.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(failed) = failures.get(serial.value())
+            && failed.pin_hash == pin_hash
+            && failed.at.elapsed() < self.debounce
+        {
+            warn!(
+                serial = %serial.redacted(),
+                "Refusing to resend a PIN that just failed"
+            );
+            return Err(YubiKeyError::pin_attempt_debounced(serial));
+        }
+
+        if retries == Some(1) && !accept_last_attempt {
+            warn!(
+                serial = %serial.redacted(),
+                "Last PIN attempt not accepted by caller"
+            );
+            return Err(YubiKeyError::last_attempt_guard(1));
+        }
+        Ok(())
+    }
+
+    /// Note how an attempt with `pin` went
+    ///
+    /// A failure counts as a wrong PIN when the counter dropped, or when it
+    /// couldn't be read either side of the attempt.
+    pub fn record(
+        &self,
+        serial: &Serial,
+        pin: &str,
+        succeeded: bool,
+        retries_before: Option<u8>,
+        retries_after: Option<u8>,
+    ) {
+        let mut failures = self.failures.lock().unwrap_or_else(|e| e.into_inner());
+        if succeeded {
+            failures.remove(serial.value());
+            return;
+        }
+
+        let wrong_pin = match (retries_before, retries_after) {
+            (Some(before), Some(after)) => after < before,
+            _ => true,
+        };
+        if wrong_pin {
+            debug!(
+                serial = %serial.redacted(),
+                retries_after = ?retries_after,
+                "Recorded failed PIN attempt"
+            );
+            failures.insert(
+                serial.value().to_string(),
+                FailedAttempt {
+                    pin_hash: self.hash(serial, pin),
+                    at: Instant::now(),
+                },
+            );
+        }
+    }
+
+    fn hash(&self, serial: &Serial, pin: &str) -> [u8; 32] {
+        let mut hasher = Sha256::new();
+        hasher.update(self.salt);
+        hasher.update(serial.value().as_bytes());
+        hasher.update([0u8]);
+        hasher.update(pin.as_bytes());
+        hasher.finalize().into()
+    }
+}
+
+impl Default for PinAttemptGuard {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The retry counter, or `None` (logged) if it couldn't be read
+///
+/// An unreadable counter doesn't stop the operation; the debounce still applies.
+pub fn read_counter(serial: &Serial, retries: YubiKeyResult<u8>) -> Option<u8> {
+    match retries {
+        Ok(retries) => Some(retries),
+        Err(e) => {
+            warn!(
+                serial = %serial.redacted(),
+                error = %e,
+                "Could not read PIN retry counter"
+            );
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn serial() -> Serial {
+        Serial::new("12345678".to_string()).unwrap()
+    }
+
+    #[test]
+    fn test_counter_checks() {
+        let guard = PinAttemptGuard::new();
+
+        assert!(guard.check(&serial(), "294761", Some(3), false).is_ok());
+        assert!(guard.check(&serial(), "294761", None, false).is_ok());
+        assert!(matches!(
+            guard.check(&serial(), "294761", Some(1), false),
+            Err(YubiKeyError::LastAttemptGuard {
+                retries_remaining: 1
+            })
+        ));
+        assert!(guard.check(&serial(), "294761", Some(1), true).is_ok());
+        assert!(matches!(
+            guard.check(&serial(), "294761", Some(0), true),
+            Err(YubiKeyError::PinBlocked { .. })
+        ));
+    }
+
+    #[test]
+    fn test_same_pin_is_debounced_after_failure() {
+        let guard = PinAttemptGuard::new();
+        guard.record(&serial(), "294761", false, Some(3), Some(2));
+
+        assert!(matches!(
+            guard.check(&serial(), "294761", Some(2), false),
+            Err(YubiKeyError::PinAttemptDebounced { .. })
+        ));
+        // A different PIN, or the same PIN on another device, goes through
+        assert!(guard.check(&serial(), "385172", Some(2), false).is_ok());
+        let other = Serial::new("87654321".to_string()).unwrap();
+        assert!(guard.check(&other, "294761", Some(3), false).is_ok());
+
+        // A success clears the failure
+        guard.record(&serial(), "385172", true, Some(2), None);
+        assert!(guard.check(&serial(), "294761", Some(3), false).is_ok());
+    }
+
+    #[test]
+    fn test_debounce_window_and_non_pin_failures() {
+        let guard = PinAttemptGuard::with_debounce(Duration::ZERO);
+        guard.record(&serial(), "294761", false, Some(3), Some(2));
+        assert!(guard.check(&serial(), "294761", Some(2), false).is_ok());
+
+        // A failure that left the counter alone wasn't a wrong PIN
+        let guard = PinAttemptGuard::new();
+        guard.record(&serial(), "294761", false, Some(3), Some(3));
+        assert!(guard.check(&serial(), "294761", Some(3), false).is_ok());
+    }
+}
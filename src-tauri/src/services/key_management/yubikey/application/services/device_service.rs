@@ -4,10 +4,13 @@
 //! It implements the DeviceService trait to provide testable abstractions
 //! over the physical device interactions.
 
+use crate::logging::spawn_blocking;
 use crate::prelude::*;
 use crate::services::key_management::yubikey::{
     domain::errors::{YubiKeyError, YubiKeyResult},
-    domain::models::{FormFactor, Interface, Pin, Serial, YubiKeyDevice},
+    domain::models::{
+        FormFactor, Interface, Pin, Serial, SmartCardReader, YubiKeyDevice, select_reader,
+    },
 };
 use async_trait::async_trait;
 use std::time::Duration;
@@ -25,8 +28,40 @@ pub trait DeviceService: Send + Sync + std::fmt::Debug {
     /// List all connected YubiKey devices
     async fn list_connected_devices(&self) -> YubiKeyResult<Vec<YubiKeyDevice>>;
 
-    /// Detect specific device by serial
-    async fn detect_device(&self, serial: &Serial) -> YubiKeyResult<Option<YubiKeyDevice>>;
+    /// List PC/SC readers with the serial of any YubiKey in each
+    async fn list_readers(&self) -> YubiKeyResult<Vec<SmartCardReader>>;
+
+    /// Detect specific device by serial, optionally on a specific reader
+    ///
+    /// With `reader_hint`, the hinted reader must hold this device; another
+    /// key (or none) there is a `ReaderSerialMismatch` error rather than the
+    /// device being looked up on a different reader.
+    async fn detect_device(
+        &self,
+        serial: &Serial,
+        reader_hint: Option<&str>,
+    ) -> YubiKeyResult<Option<YubiKeyDevice>> {
+        debug!("Detecting YubiKey device: {}", serial.redacted());
+
+        let device = self
+            .list_connected_devices()
+            .await?
+            .into_iter()
+            .find(|d| d.serial() == serial);
+
+        let Some(hint) = reader_hint else {
+            return Ok(device);
+        };
+
+        let readers = self.list_readers().await?;
+        let reader = select_reader(&readers, serial, Some(hint))?;
+        debug!(
+            "YubiKey {} found on reader '{}'",
+            serial.redacted(),
+            reader.name
+        );
+        Ok(device.map(|device| device.with_reader(reader.name.clone())))
+    }
 
     /// Check if device is connected
     async fn is_device_connected(&self, serial: &Serial) -> YubiKeyResult<bool>;
@@ -37,6 +72,11 @@ pub trait DeviceService: Send + Sync + std::fmt::Debug {
     /// Check if device has default PIN (123456)
     async fn has_default_pin(&self, serial: &Serial) -> YubiKeyResult<bool>;
 
+    /// PIN attempts left before the PIV applet blocks the PIN
+    ///
+    /// Reading the counter doesn't spend an attempt.
+    async fn get_pin_retries(&self, serial: &Serial) -> YubiKeyResult<u8>;
+
     /// Get device firmware version
     async fn get_firmware_version(&self, serial: &Serial) -> YubiKeyResult<Option<String>>;
 
@@ -264,35 +304,75 @@ fn extract_firmware_version(&self, parts: &[&str]) -> Option<String> {
     }
 }
 
+/// Reader names from `ykman list --readers`, one per line
+fn parse_reader_list(output: &str) -> Vec<String> {
+    output
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Serial from `ykman info` output ("Serial number: 12345678")
+fn parse_info_serial(output: &str) -> Option<Serial> {
+    output.lines().find_map(|line| {
+        let (field, value) = line.split_once(':')?;
+        let field = field.trim();
+        if field == "Serial number" || field == "Serial" {
+            Serial::new(value.trim().to_string()).ok()
+        } else {
+            None
+        }
+    })
+}
+
 #[async_trait]
 impl DeviceService for YkmanDeviceService {
     async fn list_connected_devices(&self) -> YubiKeyResult<Vec<YubiKeyDevice>> {
         debug!("Listing connected YubiKey devices");
 
         let output = self.run_ykman_command(vec!["list".to_string()]).await?;
-        let devices = self.parse_device_list(&output)?;
+        let mut devices = self.parse_device_list(&output)?;
+
+        // Reader names are informational here, so a failure doesn't fail the listing
+        match self.list_readers().await {
+            Ok(readers) => {
+                for device in &mut devices {
+                    if let Some(reader) = readers.iter().find(|r| r.holds(device.serial())) {
+                        device.reader_name = Some(reader.name.clone());
+                    }
+                }
+            }
+            Err(e) => warn!("Failed to list smart-card readers: {}", e),
+        }
 
         debug!("Found {} connected YubiKey devices", devices.len());
         Ok(devices)
     }
 
-    async fn detect_device(&self, serial: &Serial) -> YubiKeyResult<Option<YubiKeyDevice>> {
-        debug!("Detecting YubiKey device: {}", serial.redacted());
-
-        let devices = self.list_connected_devices().await?;
-        let device = devices.into_iter().find(|d| d.serial() == serial);
-
-        if device.is_some() {
-            debug!("YubiKey device found: {}", serial.redacted());
-        } else {
-            debug!("YubiKey device not found: {}", serial.redacted());
+    async fn list_readers(&self) -> YubiKeyResult<Vec<SmartCardReader>> {
+        let output = self
+            .run_ykman_command(vec!["list".to_string(), "--readers".to_string()])
+            .await?;
+
+        let mut readers = Vec::new();
+        for name in parse_reader_list(&output) {
+            // Readers holding other cards (or none) make `info` fail
+            let args = vec!["--reader".to_string(), name.clone(), "info".to_string()];
+            let serial = match self.run_ykman_command(args).await {
+                Ok(info) => parse_info_serial(&info),
+                Err(_) => None,
+            };
+            readers.push(SmartCardReader::new(name, serial));
         }
 
-        Ok(device)
+        debug!("Found {} smart-card readers", readers.len());
+        Ok(readers)
     }
 
     async fn is_device_connected(&self, serial: &Serial) -> YubiKeyResult<bool> {
-        let device = self.detect_device(serial).await?;
+        let device = self.detect_device(serial, None).await?;
         Ok(device.is_some())
     }
 
@@ -316,7 +396,7 @@ async fn has_default_pin(&self, serial: &Serial) -> YubiKeyResult<bool> {
         use crate::services::key_management::yubikey::infrastructure::pty::ykman_ops::has_default_pin;
 
         let serial_value = serial.value().to_string();
-        let result = tokio::task::spawn_blocking(move || {
+        let result = spawn_blocking(move || {
             has_default_pin(&serial_value)
                 .map_err(|e| YubiKeyError::device(format!("Default PIN check failed: {}", e)))
         })
@@ -332,6 +412,18 @@ async fn has_default_pin(&self, serial: &Serial) -> YubiKeyResult<bool> {
         Ok(result)
     }
 
+    async fn get_pin_retries(&self, serial: &Serial) -> YubiKeyResult<u8> {
+        use crate::services::key_management::yubikey::infrastructure::pty::ykman_ops::get_pin_retries;
+
+        let serial_value = serial.value().to_string();
+        spawn_blocking(move || {
+            get_pin_retries(&serial_value)
+                .map_err(|e| YubiKeyError::device(format!("PIN retry check failed: {}", e)))
+        })
+        .await
+        .map_err(|e| YubiKeyError::device(format!("Task join error: {}", e)))?
+    }
+
     async fn get_firmware_version(&self, serial: &Serial) -> YubiKeyResult<Option<String>> {
         debug!(
             "Getting firmware version for YubiKey: {}",
@@ -417,6 +509,22 @@ fn test_device_list_parsing() {
         assert_eq!(devices[1].serial().value(), "87654321");
     }
 
+    #[test]
+    fn test_reader_list_parsing() {
+        let readers = "Identiv uTrust 2700 R Smart Card Reader\nYubico YubiKey OTP+FIDO+CCID\n\n";
+        assert_eq!(
+            parse_reader_list(readers),
+            vec![
+                "Identiv uTrust 2700 R Smart Card Reader",
+                "Yubico YubiKey OTP+FIDO+CCID"
+            ]
+        );
+
+        let info = "Device type: YubiKey 5 NFC\nSerial number: 12345678\nFirmware version: 5.4.3";
+        assert_eq!(parse_info_serial(info).unwrap().value(), "12345678");
+        assert!(parse_info_serial("Device type: badge").is_none());
+    }
+
     #[test]
     fn test_interface_extraction() {
         let service = YkmanDeviceService::with_ykman_path("ykman".to_string());
@@ -3,10 +3,12 @@
 //! This service handles age-plugin-yubikey operations and fixes the critical
 //! identity tag bug by centralizing identity management and validation.
 
+use crate::logging::spawn_blocking;
 use crate::prelude::*;
 use crate::services::key_management::yubikey::{
     domain::errors::{YubiKeyError, YubiKeyResult},
     domain::models::{Pin, Serial, YubiKeyIdentity},
+    infrastructure::age::plugin_protocol::check_plugin_failure,
 };
 use async_trait::async_trait;
 use std::path::PathBuf;
@@ -208,6 +210,9 @@ async fn run_plugin_command(
         } else {
             let stderr = String::from_utf8_lossy(&output.stderr);
             error!("age-plugin-yubikey command failed: {}", stderr);
+            if let Some(mismatch) = check_plugin_failure(&self.plugin_path, &stderr).await {
+                return Err(mismatch);
+            }
             Err(YubiKeyError::age_plugin_command_failed(
                 &args.join(" "),
                 output.status.code().unwrap_or(-1),
@@ -375,24 +380,36 @@ async fn generate_identity(
         // Use PTY-based execution for PIN interaction
         // Platform-specific: Windows uses version with DSR response and CRLF line endings
         #[cfg(target_os = "windows")]
-        let output = tokio::task::spawn_blocking({
+        let output = spawn_blocking({
             let args_clone = args.clone();
             let pin_clone = pin.value().to_string();
             move || run_age_plugin_yubikey_windows(args_clone, Some(&pin_clone), true)
         })
         .await
-        .map_err(|e| YubiKeyError::device(format!("Task join error: {}", e)))?
-        .map_err(|e| YubiKeyError::device(format!("age-plugin-yubikey failed: {}", e)))?;
+        .map_err(|e| YubiKeyError::device(format!("Task join error: {}", e)))?;
 
         #[cfg(not(target_os = "windows"))]
-        let output = tokio::task::spawn_blocking({
+        let output = spawn_blocking({
             let args_clone = args.clone();
             let pin_clone = pin.value().to_string();
             move || run_age_plugin_yubikey(args_clone, Some(&pin_clone), true)
         })
         .await
-        .map_err(|e| YubiKeyError::device(format!("Task join error: {}", e)))?
-        .map_err(|e| YubiKeyError::device(format!("age-plugin-yubikey failed: {}", e)))?;
+        .map_err(|e| YubiKeyError::device(format!("Task join error: {}", e)))?;
+
+        let output = match output {
+            Ok(output) => output,
+            Err(e) => {
+                let transcript = e.to_string();
+                if let Some(mismatch) = check_plugin_failure(&self.plugin_path, &transcript).await {
+                    return Err(mismatch);
+                }
+                return Err(YubiKeyError::device(format!(
+                    "age-plugin-yubikey failed: {}",
+                    transcript
+                )));
+            }
+        };
 
         debug!(
             output_length = output.len(),
@@ -448,6 +465,7 @@ async fn get_existing_identity(
                     Ok(None)
                 }
             },
+            Err(e @ YubiKeyError::PluginProtocolMismatch { .. }) => Err(e),
             Err(_) => {
                 debug!(
                     serial = %serial.redacted(),
@@ -10,7 +10,7 @@
     domain::errors::{YubiKeyError, YubiKeyResult},
     domain::models::{Serial, YubiKeyDevice, YubiKeyIdentity},
 };
-use crate::services::shared::infrastructure::sanitize_label;
+use crate::services::shared::infrastructure::{ChangeEvent, publish, sanitize_label};
 use async_trait::async_trait;
 
 /// Registry service trait for key registry operations
@@ -210,6 +210,12 @@ async fn add_yubikey_entry(
 
         // Save registry
         self.save_registry(&registry).await?;
+        if let Some(entry) = registry.get_key(&key_id) {
+            publish(ChangeEvent::KeyAdded {
+                key_id: key_id.clone(),
+                label: entry.label().to_string(),
+            });
+        }
 
         Ok(key_id)
     }
@@ -328,6 +334,7 @@ async fn update_label(&self, key_id: &str, new_label: String) -> YubiKeyResult<(
                 vault_associations,
                 deactivated_at,
                 previous_lifecycle_status,
+                last_reader,
             } => KeyEntry::Yubikey {
                 label: new_label,
                 created_at,
@@ -345,6 +352,7 @@ async fn update_label(&self, key_id: &str, new_label: String) -> YubiKeyResult<(
                 vault_associations,
                 deactivated_at,
                 previous_lifecycle_status,
+                last_reader,
             },
             _ => return Err(YubiKeyError::registry("Not a YubiKey entry".to_string())),
         };
@@ -1,12 +1,16 @@
 //! Tests for service module
 
 use super::*;
+use crate::services::key_management::yubikey::application::{
+    PinAttemptGuard, YubiKeyManager, YubiKeyManagerConfig,
+};
+use crate::services::key_management::yubikey::domain::errors::YubiKeyError;
 use crate::services::key_management::yubikey::domain::errors::YubiKeyResult;
 use crate::services::key_management::yubikey::domain::models::{
-    Serial, YubiKeyDevice, YubiKeyIdentity,
+    FormFactor, Interface, Pin, Serial, SmartCardReader, YubiKeyDevice, YubiKeyIdentity,
 };
 use async_trait::async_trait;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 
 #[test]
 fn test_operation_context() {
@@ -71,13 +75,8 @@ async fn list_connected_devices(
         Ok(vec![])
     }
 
-    async fn detect_device(
-        &self,
-        _serial: &Serial,
-    ) -> YubiKeyResult<
-        Option<crate::services::key_management::yubikey::domain::models::YubiKeyDevice>,
-    > {
-        Ok(None)
+    async fn list_readers(&self) -> YubiKeyResult<Vec<SmartCardReader>> {
+        Ok(vec![])
     }
 
     async fn is_device_connected(&self, _serial: &Serial) -> YubiKeyResult<bool> {
@@ -96,6 +95,10 @@ async fn has_default_pin(&self, _serial: &Serial) -> YubiKeyResult<bool> {
         Ok(false)
     }
 
+    async fn get_pin_retries(&self, _serial: &Serial) -> YubiKeyResult<u8> {
+        Ok(3)
+    }
+
     async fn get_firmware_version(&self, _serial: &Serial) -> YubiKeyResult<Option<String>> {
         Ok(Some("1.0.0".to_string()))
     }
@@ -105,6 +108,106 @@ async fn get_capabilities(&self, _serial: &Serial) -> YubiKeyResult<Vec<String>>
     }
 }
 
+/// A badge reader plus a YubiKey, with the YubiKey on the second reader
+#[derive(Debug)]
+struct TwoReaderDeviceService;
+
+const BADGE_READER: &str = "Identiv uTrust 2700 R Smart Card Reader";
+const YUBIKEY_READER: &str = "Yubico YubiKey OTP+FIDO+CCID";
+
+fn target_serial() -> Serial {
+    Serial::new("12345678".to_string()).unwrap()
+}
+
+#[async_trait]
+impl DeviceService for TwoReaderDeviceService {
+    async fn list_connected_devices(&self) -> YubiKeyResult<Vec<YubiKeyDevice>> {
+        Ok(vec![YubiKeyDevice::from_detected_device(
+            target_serial(),
+            "YubiKey 5 NFC".to_string(),
+            FormFactor::NFC,
+            vec![Interface::USB],
+            Some("5.4.3".to_string()),
+        )])
+    }
+
+    async fn list_readers(&self) -> YubiKeyResult<Vec<SmartCardReader>> {
+        Ok(vec![
+            SmartCardReader::new(BADGE_READER, None),
+            SmartCardReader::new(YUBIKEY_READER, Some(target_serial())),
+        ])
+    }
+
+    async fn is_device_connected(&self, serial: &Serial) -> YubiKeyResult<bool> {
+        Ok(self.detect_device(serial, None).await?.is_some())
+    }
+
+    async fn validate_pin(
+        &self,
+        _serial: &Serial,
+        _pin: &crate::services::key_management::yubikey::domain::models::Pin,
+    ) -> YubiKeyResult<bool> {
+        Ok(true)
+    }
+
+    async fn has_default_pin(&self, _serial: &Serial) -> YubiKeyResult<bool> {
+        Ok(false)
+    }
+
+    async fn get_pin_retries(&self, _serial: &Serial) -> YubiKeyResult<u8> {
+        Ok(3)
+    }
+
+    async fn get_firmware_version(&self, _serial: &Serial) -> YubiKeyResult<Option<String>> {
+        Ok(Some("5.4.3".to_string()))
+    }
+
+    async fn get_capabilities(&self, _serial: &Serial) -> YubiKeyResult<Vec<String>> {
+        Ok(vec!["PIV".to_string()])
+    }
+}
+
+#[tokio::test]
+async fn test_detect_device_on_second_reader() {
+    let service = TwoReaderDeviceService;
+
+    let device = service
+        .detect_device(&target_serial(), Some("yubikey"))
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(device.reader_name.as_deref(), Some(YUBIKEY_READER));
+
+    // Without a hint the key is still found, wherever it is
+    let device = service.detect_device(&target_serial(), None).await.unwrap();
+    assert!(device.is_some());
+}
+
+#[tokio::test]
+async fn test_hinted_reader_with_other_card_is_mismatch() {
+    let service = TwoReaderDeviceService;
+
+    let err = service
+        .detect_device(&target_serial(), Some("Identiv"))
+        .await
+        .unwrap_err();
+    assert!(matches!(err, YubiKeyError::ReaderSerialMismatch { .. }));
+
+    // The right reader with a different key expected is a mismatch too
+    let other = Serial::new("87654321".to_string()).unwrap();
+    let err = service
+        .detect_device(&other, Some(YUBIKEY_READER))
+        .await
+        .unwrap_err();
+    assert!(matches!(err, YubiKeyError::ReaderSerialMismatch { .. }));
+
+    let err = service
+        .detect_device(&target_serial(), Some("Gemalto"))
+        .await
+        .unwrap_err();
+    assert!(matches!(err, YubiKeyError::ReaderNotFound { .. }));
+}
+
 #[derive(Debug)]
 struct MockIdentityService;
 
@@ -421,3 +524,156 @@ async fn test_service_factory_shutdown() {
 
     assert!(factory.shutdown_all_services().await.is_ok());
 }
+
+/// A YubiKey whose PIV applet counts PIN attempts like the real one
+#[derive(Debug)]
+struct PinCounterDeviceService {
+    pin: &'static str,
+    retries: Mutex<u8>,
+    /// PINs that actually reached the device
+    sent: Mutex<u32>,
+}
+
+impl PinCounterDeviceService {
+    fn new(pin: &'static str, retries: u8) -> Self {
+        Self {
+            pin,
+            retries: Mutex::new(retries),
+            sent: Mutex::new(0),
+        }
+    }
+
+    /// Verify `pin` the way the applet does: a success resets the counter
+    fn verify(&self, pin: &Pin) -> YubiKeyResult<()> {
+        *self.sent.lock().unwrap() += 1;
+        let mut retries = self.retries.lock().unwrap();
+        if *retries == 0 {
+            return Err(YubiKeyError::pin_blocked(&target_serial()));
+        }
+        if pin.value() == self.pin {
+            *retries = 3;
+            Ok(())
+        } else {
+            *retries -= 1;
+            Err(YubiKeyError::pin("Wrong PIN"))
+        }
+    }
+
+    fn sent(&self) -> u32 {
+        *self.sent.lock().unwrap()
+    }
+}
+
+#[async_trait]
+impl DeviceService for PinCounterDeviceService {
+    async fn list_connected_devices(&self) -> YubiKeyResult<Vec<YubiKeyDevice>> {
+        Ok(vec![])
+    }
+
+    async fn list_readers(&self) -> YubiKeyResult<Vec<SmartCardReader>> {
+        Ok(vec![])
+    }
+
+    async fn is_device_connected(&self, _serial: &Serial) -> YubiKeyResult<bool> {
+        Ok(true)
+    }
+
+    async fn validate_pin(&self, _serial: &Serial, _pin: &Pin) -> YubiKeyResult<bool> {
+        Ok(true)
+    }
+
+    async fn has_default_pin(&self, _serial: &Serial) -> YubiKeyResult<bool> {
+        Ok(false)
+    }
+
+    async fn get_pin_retries(&self, _serial: &Serial) -> YubiKeyResult<u8> {
+        Ok(*self.retries.lock().unwrap())
+    }
+
+    async fn get_firmware_version(&self, _serial: &Serial) -> YubiKeyResult<Option<String>> {
+        Ok(Some("5.4.3".to_string()))
+    }
+
+    async fn get_capabilities(&self, _serial: &Serial) -> YubiKeyResult<Vec<String>> {
+        Ok(vec!["PIV".to_string()])
+    }
+}
+
+fn pin_counter_manager(device: Arc<PinCounterDeviceService>) -> YubiKeyManager {
+    let factory = ServiceFactory::with_services(
+        device,
+        Arc::new(MockIdentityService),
+        Arc::new(MockRegistryService),
+        Arc::new(MockFileService),
+    );
+    YubiKeyManager::with_services(factory, YubiKeyManagerConfig::default())
+        .with_pin_guard(Arc::new(PinAttemptGuard::new()))
+}
+
+#[tokio::test]
+async fn test_pin_guard_stops_before_the_final_attempt() {
+    let device = Arc::new(PinCounterDeviceService::new("529374", 2));
+    let manager = pin_counter_manager(device.clone());
+    let serial = target_serial();
+    let wrong = Pin::new("294761".to_string()).unwrap();
+    let also_wrong = Pin::new("385172".to_string()).unwrap();
+    let correct = Pin::new("529374".to_string()).unwrap();
+    let send = |pin: &Pin, accept_last_attempt: bool| {
+        let pin = pin.clone();
+        let device = device.clone();
+        let manager = &manager;
+        let serial = serial.clone();
+        async move {
+            manager
+                .guard_pin_attempt(&serial, &pin, accept_last_attempt, async {
+                    device.verify(&pin)
+                })
+                .await
+        }
+    };
+
+    // 2 -> 1: a wrong PIN reaches the device
+    let err = send(&wrong, false).await.unwrap_err();
+    assert!(matches!(err, YubiKeyError::Pin { .. }));
+    assert_eq!(manager.get_pin_retries(&serial).await.unwrap(), 1);
+
+    // Resubmitting the same PIN right away doesn't
+    let err = send(&wrong, false).await.unwrap_err();
+    assert!(matches!(err, YubiKeyError::PinAttemptDebounced { .. }));
+
+    // Nor does the final attempt without the caller accepting it
+    let err = send(&also_wrong, false).await.unwrap_err();
+    assert!(matches!(
+        err,
+        YubiKeyError::LastAttemptGuard {
+            retries_remaining: 1
+        }
+    ));
+    assert_eq!(device.sent(), 1);
+    assert_eq!(manager.get_pin_retries(&serial).await.unwrap(), 1);
+
+    // 1 -> blocked once it's accepted and wrong
+    let err = send(&also_wrong, true).await.unwrap_err();
+    assert!(matches!(err, YubiKeyError::Pin { .. }));
+    assert_eq!(manager.get_pin_retries(&serial).await.unwrap(), 0);
+
+    // A blocked PIN isn't sent at all, even the right one
+    let err = send(&correct, true).await.unwrap_err();
+    assert!(matches!(err, YubiKeyError::PinBlocked { .. }));
+    assert_eq!(device.sent(), 2);
+}
+
+#[tokio::test]
+async fn test_accepted_last_attempt_with_right_pin_resets_counter() {
+    let device = Arc::new(PinCounterDeviceService::new("529374", 1));
+    let manager = pin_counter_manager(device.clone());
+    let serial = target_serial();
+    let correct = Pin::new("529374".to_string()).unwrap();
+
+    manager
+        .guard_pin_attempt(&serial, &correct, true, async { device.verify(&correct) })
+        .await
+        .unwrap();
+    assert_eq!(manager.get_pin_retries(&serial).await.unwrap(), 3);
+    assert_eq!(device.sent(), 1);
+}
@@ -4,8 +4,8 @@
 //! the scattered error handling patterns found throughout the codebase.
 
 use crate::services::key_management::yubikey::domain::models::{
-    IdentityValidationError, PinValidationError, Serial, SerialValidationError,
-    StateTransitionError,
+    IdentityValidationError, PinValidationError, PluginCompatibility, PluginVersion,
+    SUPPORTED_PLUGIN_VERSIONS, Serial, SerialValidationError, StateTransitionError,
 };
 use std::fmt;
 
@@ -27,6 +27,18 @@ pub enum YubiKeyError {
     #[error("Multiple YubiKey devices found, specify serial: {serials:?}")]
     MultipleDevicesFound { serials: Vec<String> },
 
+    /// Hinted smart-card reader isn't attached
+    #[error("Smart-card reader not found: {reader}")]
+    ReaderNotFound { reader: String },
+
+    /// Hinted reader holds a different YubiKey (or none) than expected
+    #[error("Reader '{reader}' holds {found}, not YubiKey {expected}")]
+    ReaderSerialMismatch {
+        reader: String,
+        expected: String,
+        found: String,
+    },
+
     /// Identity-related errors (this fixes the identity tag bug)
     #[error("Identity error: {message}")]
     Identity { message: String },
@@ -81,6 +93,14 @@ pub enum YubiKeyError {
     #[error("PIN is blocked for YubiKey: {serial}")]
     PinBlocked { serial: String },
 
+    /// One PIN attempt left and the caller didn't accept spending it
+    #[error("Only {retries_remaining} PIN attempt left before the YubiKey PIN blocks")]
+    LastAttemptGuard { retries_remaining: u8 },
+
+    /// The same PIN failed on this YubiKey moments ago
+    #[error("This PIN was just rejected by YubiKey {serial}")]
+    PinAttemptDebounced { serial: String },
+
     /// Serial number errors
     #[error("Serial validation failed: {source}")]
     SerialValidation {
@@ -137,6 +157,15 @@ pub enum YubiKeyError {
         stderr: String,
     },
 
+    /// age-plugin-yubikey speaks a plugin protocol our age library doesn't
+    #[error(
+        "age-plugin-yubikey {plugin_version} is not compatible with this app (supported: {supported_range})"
+    )]
+    PluginProtocolMismatch {
+        plugin_version: String,
+        supported_range: String,
+    },
+
     /// Configuration errors
     #[error("Configuration error: {message}")]
     Configuration { message: String },
@@ -200,6 +229,25 @@ pub fn multiple_devices_found(serials: &[Serial]) -> Self {
         }
     }
 
+    /// Create a reader not found error
+    pub fn reader_not_found(reader: &str) -> Self {
+        Self::ReaderNotFound {
+            reader: reader.to_string(),
+        }
+    }
+
+    /// Create a reader serial mismatch error
+    pub fn reader_serial_mismatch(reader: &str, expected: &Serial, found: Option<&Serial>) -> Self {
+        Self::ReaderSerialMismatch {
+            reader: reader.to_string(),
+            expected: expected.redacted(),
+            found: found.map_or_else(
+                || "no YubiKey".to_string(),
+                |serial| format!("YubiKey {}", serial.redacted()),
+            ),
+        }
+    }
+
     /// Create an identity error
     pub fn identity(message: impl Into<String>) -> Self {
         Self::Identity {
@@ -270,6 +318,18 @@ pub fn pin_blocked(serial: &Serial) -> Self {
         }
     }
 
+    /// Create a last attempt guard error
+    pub fn last_attempt_guard(retries_remaining: u8) -> Self {
+        Self::LastAttemptGuard { retries_remaining }
+    }
+
+    /// Create a PIN attempt debounced error
+    pub fn pin_attempt_debounced(serial: &Serial) -> Self {
+        Self::PinAttemptDebounced {
+            serial: serial.redacted(),
+        }
+    }
+
     /// Create a state error
     pub fn state(message: impl Into<String>) -> Self {
         Self::State {
@@ -332,6 +392,14 @@ pub fn age_plugin_command_failed(command: &str, exit_code: i32, stderr: &str) ->
         }
     }
 
+    /// Create a plugin protocol mismatch error
+    pub fn plugin_protocol_mismatch(plugin_version: &str, supported_range: &str) -> Self {
+        Self::PluginProtocolMismatch {
+            plugin_version: plugin_version.to_string(),
+            supported_range: supported_range.to_string(),
+        }
+    }
+
     /// Create a configuration error
     pub fn configuration(message: impl Into<String>) -> Self {
         Self::Configuration {
@@ -373,20 +441,81 @@ pub fn is_recoverable(&self) -> bool {
             // Non-recoverable errors
             Self::DeviceNotFound { .. } => false,
             Self::PinBlocked { .. } => false,
+            Self::LastAttemptGuard { .. } => true,
+            Self::PinAttemptDebounced { .. } => true,
             Self::SerialValidation { .. } => false,
             Self::AgePluginNotFound { .. } => false,
+            Self::PluginProtocolMismatch { .. } => false,
 
             // Context-dependent
             _ => false,
         }
     }
 
+    /// What the user should do about the error, where there's a specific answer
+    pub fn recovery_guidance(&self) -> Option<String> {
+        match self {
+            Self::PluginProtocolMismatch {
+                plugin_version,
+                supported_range,
+            } => {
+                let compatibility = PluginVersion::parse(plugin_version)
+                    .map(|version| SUPPORTED_PLUGIN_VERSIONS.check(version));
+                Some(match compatibility {
+                    Some(PluginCompatibility::PluginTooNew) => format!(
+                        "The installed age-plugin-yubikey is newer than this app supports. \
+                         Update Barqly Vault, or install a plugin version {supported_range}"
+                    ),
+                    Some(PluginCompatibility::PluginTooOld) => format!(
+                        "Update age-plugin-yubikey to a version {supported_range}, \
+                         or reinstall Barqly Vault to restore the bundled plugin"
+                    ),
+                    _ => format!(
+                        "Reinstall Barqly Vault to restore the bundled age-plugin-yubikey \
+                         (supported versions: {supported_range})"
+                    ),
+                })
+            }
+            Self::ReaderNotFound { .. } => Some(
+                "Check the reader is connected, or choose another reader for this YubiKey"
+                    .to_string(),
+            ),
+            Self::ReaderSerialMismatch {
+                reader, expected, ..
+            } => Some(format!(
+                "Insert YubiKey {expected} into '{reader}', or choose the reader it's in"
+            )),
+            Self::LastAttemptGuard { .. } => Some(
+                "Double-check the PIN before confirming: a wrong PIN now blocks it, \
+                 and unblocking needs the PUK"
+                    .to_string(),
+            ),
+            Self::PinAttemptDebounced { .. } => Some(
+                "Check the PIN and type it again; the same PIN won't be sent twice in a row"
+                    .to_string(),
+            ),
+            _ => None,
+        }
+    }
+
+    /// Whether the PIN guard refused to send the PIN to the device
+    pub fn is_pin_guard(&self) -> bool {
+        matches!(
+            self,
+            Self::PinBlocked { .. }
+                | Self::LastAttemptGuard { .. }
+                | Self::PinAttemptDebounced { .. }
+        )
+    }
+
     /// Get error category for metrics/logging
     pub fn category(&self) -> ErrorCategory {
         match self {
             Self::Device { .. }
             | Self::DeviceNotFound { .. }
-            | Self::MultipleDevicesFound { .. } => ErrorCategory::Device,
+            | Self::MultipleDevicesFound { .. }
+            | Self::ReaderNotFound { .. }
+            | Self::ReaderSerialMismatch { .. } => ErrorCategory::Device,
             Self::Identity { .. }
             | Self::IdentityNotFound { .. }
             | Self::IdentityValidation { .. } => ErrorCategory::Identity,
@@ -396,9 +525,11 @@ pub fn category(&self) -> ErrorCategory {
             Self::File { .. } | Self::Encryption { .. } | Self::Decryption { .. } => {
                 ErrorCategory::File
             }
-            Self::Pin { .. } | Self::PinValidation { .. } | Self::PinBlocked { .. } => {
-                ErrorCategory::Pin
-            }
+            Self::Pin { .. }
+            | Self::PinValidation { .. }
+            | Self::PinBlocked { .. }
+            | Self::LastAttemptGuard { .. }
+            | Self::PinAttemptDebounced { .. } => ErrorCategory::Pin,
             Self::State { .. }
             | Self::StateTransition { .. }
             | Self::OperationNotAllowed { .. } => ErrorCategory::State,
@@ -407,15 +538,17 @@ pub fn category(&self) -> ErrorCategory {
             }
             Self::AgePlugin { .. }
             | Self::AgePluginNotFound { .. }
-            | Self::AgePluginCommandFailed { .. } => ErrorCategory::AgePlugin,
+            | Self::AgePluginCommandFailed { .. }
+            | Self::PluginProtocolMismatch { .. } => ErrorCategory::AgePlugin,
             Self::SerialValidation { .. } | Self::SerialRequired { .. } => ErrorCategory::Serial,
             _ => ErrorCategory::Other,
         }
     }
 }
 
-/// Error categories for metrics and logging
-#[derive(Debug, Clone, PartialEq, Eq)]
+/// Error categories for metrics, logging and troubleshooting help
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, specta::Type)]
+#[serde(rename_all = "snake_case")]
 pub enum ErrorCategory {
     Device,
     Identity,
@@ -32,6 +32,9 @@ pub struct YubiKeyDevice {
     pub slots: Vec<SlotInfo>,
     /// Additional metadata
     pub metadata: HashMap<String, String>,
+    /// Smart-card reader the device was found on
+    #[serde(default)]
+    pub reader_name: Option<String>,
 }
 
 impl YubiKeyDevice {
@@ -53,6 +56,7 @@ pub fn new(
             health: DeviceHealth::Unknown,
             slots: Vec::new(),
             metadata: HashMap::new(),
+            reader_name: None,
         }
     }
 
@@ -76,6 +80,12 @@ pub fn from_detected_device(
         device
     }
 
+    /// Record the reader the device was found on
+    pub fn with_reader(mut self, reader_name: impl Into<String>) -> Self {
+        self.reader_name = Some(reader_name.into());
+        self
+    }
+
     /// Get the device serial number
     pub fn serial(&self) -> &Serial {
         &self.serial
@@ -4,11 +4,12 @@
 //! This fixes the critical identity tag bug by centralizing identity operations.
 
 use crate::services::key_management::yubikey::domain::models::serial::Serial;
+use crate::types::Sensitive;
 use serde::{Deserialize, Serialize};
 use std::fmt;
 
 /// YubiKey identity with validation and standardized format
-#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct YubiKeyIdentity {
     /// The age-plugin identity tag (e.g., "AGE-PLUGIN-YUBIKEY-...")
     identity_tag: String,
@@ -184,6 +185,20 @@ pub fn unique_id(&self) -> String {
     }
 }
 
+impl fmt::Debug for YubiKeyIdentity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("YubiKeyIdentity")
+            .field("identity_tag", &Sensitive::new(&self.identity_tag))
+            .field("serial", &self.serial)
+            .field("recipient", &Sensitive::new(&self.recipient))
+            .field("public_key", &Sensitive::new(&self.public_key))
+            .field("slot", &self.slot)
+            .field("algorithm", &self.algorithm)
+            .field("created_at", &self.created_at)
+            .finish()
+    }
+}
+
 impl fmt::Display for YubiKeyIdentity {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(
@@ -358,6 +373,22 @@ fn test_identity_creation() {
         assert!(identity.slot().is_none());
     }
 
+    #[test]
+    fn test_debug_is_redacted() {
+        let tag = create_test_identity_tag();
+        let identity = YubiKeyIdentity::new(
+            tag.clone(),
+            create_test_serial(),
+            "age1yubikey1test123".to_string(),
+        )
+        .unwrap();
+
+        let debug = format!("{identity:?}");
+        assert!(!debug.contains(&tag));
+        assert!(!debug.contains("age1yubikey1test123"));
+        assert!(!debug.contains("12345678"));
+    }
+
     #[test]
     fn test_identity_validation() {
         let serial = create_test_serial();
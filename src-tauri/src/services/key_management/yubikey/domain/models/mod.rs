@@ -5,6 +5,8 @@
 pub mod identity;
 pub mod initialization;
 pub mod pin;
+pub mod plugin_protocol;
+pub mod reader;
 pub mod serial;
 pub mod state;
 pub mod yubikey_state_info;
@@ -23,6 +25,11 @@
     policy_config,
 };
 pub use pin::{Pin, PinValidationError};
+pub use plugin_protocol::{
+    PLUGIN_PROTOCOL_VERSION, PluginCompatibility, PluginProtocolInfo, PluginVersion,
+    PluginVersionRange, SUPPORTED_PLUGIN_VERSIONS, classify_plugin_failure,
+};
+pub use reader::{SmartCardReader, select_reader};
 pub use serial::{Serial, SerialValidationError};
 pub use state::{
     PinStatus, StateTransition, StateTransitionError, YubiKeyOperation, YubiKeyState,
@@ -0,0 +1,265 @@
+//! age-plugin-yubikey protocol compatibility
+//!
+//! The age crate speaks a fixed version of the plugin protocol
+//! (`recipient-v1` / `identity-v1` state machines). When the bundled
+//! age-plugin-yubikey is newer or older than the releases that speak it, the
+//! plugin fails in ways age reports as a generic plugin failure. This module
+//! classifies the plugin's advertised version and failure transcript so the
+//! mismatch can be reported explicitly.
+
+use crate::services::key_management::yubikey::domain::errors::YubiKeyError;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Plugin protocol version spoken by our age crate
+pub const PLUGIN_PROTOCOL_VERSION: &str = "v1";
+
+/// age-plugin-yubikey releases that speak `PLUGIN_PROTOCOL_VERSION`
+pub const SUPPORTED_PLUGIN_VERSIONS: PluginVersionRange = PluginVersionRange {
+    min: PluginVersion::new(0, 4, 0),
+    max_exclusive: PluginVersion::new(0, 6, 0),
+};
+
+/// Failure output that points at a protocol disagreement rather than a
+/// device problem (lowercased)
+const PROTOCOL_FAILURE_MARKERS: [&str; 5] = [
+    "state machine",
+    "--age-plugin",
+    "plugin protocol",
+    "unsupported protocol",
+    "wasn't expected",
+];
+
+/// A semantic version advertised by the plugin
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct PluginVersion {
+    pub major: u64,
+    pub minor: u64,
+    pub patch: u64,
+}
+
+impl PluginVersion {
+    pub const fn new(major: u64, minor: u64, patch: u64) -> Self {
+        Self {
+            major,
+            minor,
+            patch,
+        }
+    }
+
+    /// Find the first `X.Y.Z` (optionally `v`-prefixed) token in `text`
+    ///
+    /// Accepts `--version` output such as "age-plugin-yubikey 0.5.0".
+    pub fn parse(text: &str) -> Option<Self> {
+        text.split_whitespace().find_map(|token| {
+            let token = token.trim_start_matches('v');
+            let core = token.split(['-', '+']).next()?;
+            let mut parts = core.split('.').map(|part| part.parse::<u64>().ok());
+            let version = Self::new(parts.next()??, parts.next()??, parts.next()??);
+            parts.next().is_none().then_some(version)
+        })
+    }
+}
+
+impl fmt::Display for PluginVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+/// Half-open range of plugin versions
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PluginVersionRange {
+    pub min: PluginVersion,
+    pub max_exclusive: PluginVersion,
+}
+
+impl PluginVersionRange {
+    pub fn check(&self, version: PluginVersion) -> PluginCompatibility {
+        if version < self.min {
+            PluginCompatibility::PluginTooOld
+        } else if version >= self.max_exclusive {
+            PluginCompatibility::PluginTooNew
+        } else {
+            PluginCompatibility::Supported
+        }
+    }
+}
+
+impl fmt::Display for PluginVersionRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, ">={}, <{}", self.min, self.max_exclusive)
+    }
+}
+
+/// How the installed plugin relates to the supported range
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+#[serde(rename_all = "snake_case")]
+pub enum PluginCompatibility {
+    Supported,
+    /// Plugin is newer than this app understands: update the app
+    PluginTooNew,
+    /// Plugin predates the protocol this app speaks: update the plugin
+    PluginTooOld,
+    /// Plugin version couldn't be determined
+    Unknown,
+}
+
+/// Plugin protocol handshake summary for diagnostics
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+pub struct PluginProtocolInfo {
+    /// Version the plugin advertised, if it could be read
+    pub plugin_version: Option<String>,
+    pub supported_range: String,
+    /// Protocol version both sides speak (None unless compatible)
+    pub negotiated_protocol: Option<String>,
+    pub compatibility: PluginCompatibility,
+}
+
+impl PluginProtocolInfo {
+    /// Classify the plugin's `--version` output
+    pub fn from_version_output(version_output: Option<&str>) -> Self {
+        let version = version_output.and_then(PluginVersion::parse);
+        let compatibility = version.map_or(PluginCompatibility::Unknown, |v| {
+            SUPPORTED_PLUGIN_VERSIONS.check(v)
+        });
+
+        Self {
+            plugin_version: version.map(|v| v.to_string()),
+            supported_range: SUPPORTED_PLUGIN_VERSIONS.to_string(),
+            negotiated_protocol: (compatibility == PluginCompatibility::Supported)
+                .then(|| PLUGIN_PROTOCOL_VERSION.to_string()),
+            compatibility,
+        }
+    }
+}
+
+/// Decide whether a failed plugin run was a protocol mismatch
+///
+/// An out-of-range advertised version is a mismatch regardless of the
+/// failure text. With an unknown version, the failure output must itself
+/// mention the protocol. A supported version never classifies as a mismatch,
+/// so real device errors keep their own message.
+pub fn classify_plugin_failure(version_output: Option<&str>, stderr: &str) -> Option<YubiKeyError> {
+    let info = PluginProtocolInfo::from_version_output(version_output);
+
+    let mismatch = match info.compatibility {
+        PluginCompatibility::Supported => false,
+        PluginCompatibility::PluginTooNew | PluginCompatibility::PluginTooOld => true,
+        PluginCompatibility::Unknown => {
+            let stderr = stderr.to_lowercase();
+            PROTOCOL_FAILURE_MARKERS
+                .iter()
+                .any(|marker| stderr.contains(marker))
+        }
+    };
+
+    mismatch.then(|| {
+        YubiKeyError::plugin_protocol_mismatch(
+            info.plugin_version.as_deref().unwrap_or("unknown"),
+            &info.supported_range,
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Handshake from a plugin release newer than our age crate understands
+    const TOO_NEW_VERSION: &str = "age-plugin-yubikey 0.7.1\n";
+    const TOO_NEW_STDERR: &str = "\
+age: error: plugin exited unexpectedly
+Error: unsupported state machine 'identity-v1', expected 'identity-v2'
+[ Did age-plugin-yubikey not do what you expected? Could an error be more useful? ]
+";
+
+    /// Handshake from a plugin release that predates the v1 state machines
+    const TOO_OLD_VERSION: &str = "age-plugin-yubikey v0.3.3\n";
+    const TOO_OLD_STDERR: &str = "\
+error: Found argument '--age-plugin' which wasn't expected, or isn't valid in this context
+
+USAGE:
+    age-plugin-yubikey [FLAGS] [OPTIONS]
+";
+
+    /// Supported plugin failing for a device reason
+    const SUPPORTED_VERSION: &str = "age-plugin-yubikey 0.5.0\n";
+    const DEVICE_STDERR: &str = "Error: Could not open YubiKey with serial 12345678\n";
+
+    #[test]
+    fn test_too_new_plugin_is_mismatch() {
+        let error = classify_plugin_failure(Some(TOO_NEW_VERSION), TOO_NEW_STDERR).unwrap();
+        assert!(matches!(
+            &error,
+            YubiKeyError::PluginProtocolMismatch { plugin_version, supported_range }
+                if plugin_version == "0.7.1" && supported_range == ">=0.4.0, <0.6.0"
+        ));
+        assert!(
+            error
+                .recovery_guidance()
+                .unwrap()
+                .contains("Update Barqly Vault")
+        );
+    }
+
+    #[test]
+    fn test_too_old_plugin_is_mismatch() {
+        let error = classify_plugin_failure(Some(TOO_OLD_VERSION), TOO_OLD_STDERR).unwrap();
+        assert!(matches!(
+            &error,
+            YubiKeyError::PluginProtocolMismatch { plugin_version, .. } if plugin_version == "0.3.3"
+        ));
+        assert!(
+            error
+                .recovery_guidance()
+                .unwrap()
+                .contains("Update age-plugin-yubikey")
+        );
+    }
+
+    #[test]
+    fn test_supported_plugin_keeps_device_error() {
+        assert!(classify_plugin_failure(Some(SUPPORTED_VERSION), DEVICE_STDERR).is_none());
+        assert!(classify_plugin_failure(Some(SUPPORTED_VERSION), TOO_NEW_STDERR).is_none());
+    }
+
+    #[test]
+    fn test_unknown_version_uses_transcript() {
+        let error = classify_plugin_failure(None, TOO_OLD_STDERR).unwrap();
+        assert!(matches!(
+            &error,
+            YubiKeyError::PluginProtocolMismatch { plugin_version, .. }
+                if plugin_version == "unknown"
+        ));
+        assert!(classify_plugin_failure(None, DEVICE_STDERR).is_none());
+    }
+
+    #[test]
+    fn test_protocol_info_reports_negotiated_version() {
+        let info = PluginProtocolInfo::from_version_output(Some(SUPPORTED_VERSION));
+        assert_eq!(info.compatibility, PluginCompatibility::Supported);
+        assert_eq!(info.negotiated_protocol.as_deref(), Some("v1"));
+
+        let info = PluginProtocolInfo::from_version_output(Some(TOO_NEW_VERSION));
+        assert_eq!(info.compatibility, PluginCompatibility::PluginTooNew);
+        assert_eq!(info.negotiated_protocol, None);
+
+        let info = PluginProtocolInfo::from_version_output(Some("garbage"));
+        assert_eq!(info.compatibility, PluginCompatibility::Unknown);
+        assert_eq!(info.plugin_version, None);
+    }
+
+    #[test]
+    fn test_version_parsing() {
+        assert_eq!(
+            PluginVersion::parse("age-plugin-yubikey 0.5.0"),
+            Some(PluginVersion::new(0, 5, 0))
+        );
+        assert_eq!(
+            PluginVersion::parse("v1.2.3-beta.1"),
+            Some(PluginVersion::new(1, 2, 3))
+        );
+        assert_eq!(PluginVersion::parse("version 0.5"), None);
+    }
+}
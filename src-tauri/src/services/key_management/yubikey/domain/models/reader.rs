@@ -0,0 +1,120 @@
+//! Smart-card readers
+//!
+//! With a YubiKey and another smart-card reader attached (say, a corporate
+//! badge reader), device detection can end up talking to the wrong reader.
+//! Readers are listed together with the serial of any YubiKey in them, so
+//! an operation can be bound to the reader that actually holds its key.
+
+use super::Serial;
+use crate::services::key_management::yubikey::domain::errors::{YubiKeyError, YubiKeyResult};
+use serde::Serialize;
+
+/// A PC/SC reader and the YubiKey in it, if any
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SmartCardReader {
+    pub name: String,
+    /// Serial of the YubiKey in the reader; `None` for other cards or an
+    /// empty reader
+    pub serial: Option<Serial>,
+}
+
+impl SmartCardReader {
+    pub fn new(name: impl Into<String>, serial: Option<Serial>) -> Self {
+        Self {
+            name: name.into(),
+            serial,
+        }
+    }
+
+    /// Whether `hint` names this reader
+    ///
+    /// Matches like ykman's `--reader`: case-insensitive, on any part of the
+    /// name.
+    pub fn matches_hint(&self, hint: &str) -> bool {
+        self.name
+            .to_lowercase()
+            .contains(&hint.trim().to_lowercase())
+    }
+
+    pub fn holds(&self, serial: &Serial) -> bool {
+        self.serial.as_ref() == Some(serial)
+    }
+}
+
+/// Pick the reader to use for the YubiKey with `serial`
+///
+/// With a hint, the hinted reader must be attached and hold that YubiKey; a
+/// different key there is an error rather than a reason to quietly use
+/// another reader. An exact name match wins over a partial one. Without a
+/// hint, the first reader holding the key is used.
+pub fn select_reader<'a>(
+    readers: &'a [SmartCardReader],
+    serial: &Serial,
+    reader_hint: Option<&str>,
+) -> YubiKeyResult<&'a SmartCardReader> {
+    let Some(hint) = reader_hint else {
+        return readers
+            .iter()
+            .find(|reader| reader.holds(serial))
+            .ok_or_else(|| YubiKeyError::device_not_found(serial));
+    };
+
+    let reader = readers
+        .iter()
+        .find(|reader| reader.name.eq_ignore_ascii_case(hint.trim()))
+        .or_else(|| readers.iter().find(|reader| reader.matches_hint(hint)))
+        .ok_or_else(|| YubiKeyError::reader_not_found(hint))?;
+
+    if reader.holds(serial) {
+        Ok(reader)
+    } else {
+        Err(YubiKeyError::reader_serial_mismatch(
+            &reader.name,
+            serial,
+            reader.serial.as_ref(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn serial(value: &str) -> Serial {
+        Serial::new(value.to_string()).unwrap()
+    }
+
+    fn readers() -> Vec<SmartCardReader> {
+        vec![
+            SmartCardReader::new("Identiv uTrust 2700 R Smart Card Reader", None),
+            SmartCardReader::new("Yubico YubiKey OTP+FIDO+CCID", Some(serial("12345678"))),
+        ]
+    }
+
+    #[test]
+    fn test_hint_matches_case_insensitive_substring() {
+        let reader = SmartCardReader::new("Yubico YubiKey OTP+FIDO+CCID 01", None);
+        assert!(reader.matches_hint("yubikey"));
+        assert!(reader.matches_hint(" CCID 01 "));
+        assert!(!reader.matches_hint("Identiv"));
+    }
+
+    #[test]
+    fn test_prefers_exact_name_over_partial_match() {
+        let readers = vec![
+            SmartCardReader::new("Yubico YubiKey CCID 01", Some(serial("11111111"))),
+            SmartCardReader::new("Yubico YubiKey CCID", Some(serial("12345678"))),
+        ];
+        let reader = select_reader(&readers, &serial("12345678"), Some("yubico yubikey ccid"));
+        assert_eq!(reader.unwrap().name, "Yubico YubiKey CCID");
+    }
+
+    #[test]
+    fn test_missing_reader_is_reported() {
+        let err = select_reader(&readers(), &serial("12345678"), Some("Gemalto")).unwrap_err();
+        assert!(matches!(err, YubiKeyError::ReaderNotFound { .. }));
+
+        let err = select_reader(&readers(), &serial("87654321"), None).unwrap_err();
+        assert!(matches!(err, YubiKeyError::DeviceNotFound { .. }));
+    }
+}
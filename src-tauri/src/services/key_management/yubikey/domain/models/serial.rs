@@ -2,11 +2,15 @@
 //!
 //! Replaces primitive obsession with proper domain modeling.
 
+use crate::types::Redactable;
 use serde::{Deserialize, Serialize};
 use std::fmt;
 
 /// YubiKey serial number with validation
-#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+///
+/// Debug shows only the last 4 digits. Display stays raw because it builds
+/// the `--serial` arguments passed to ykman and age-plugin-yubikey.
+#[derive(Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct Serial(String);
 
 impl Serial {
@@ -67,6 +71,18 @@ fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
     }
 }
 
+impl fmt::Debug for Serial {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Serial({})", self.redacted())
+    }
+}
+
+impl Redactable for Serial {
+    fn redacted(&self) -> String {
+        Serial::redacted(self)
+    }
+}
+
 impl From<Serial> for String {
     fn from(serial: Serial) -> Self {
         serial.0
@@ -161,6 +177,12 @@ fn test_redacted() {
         // assert_eq!(serial.redacted(), "****");
     }
 
+    #[test]
+    fn test_debug_is_redacted() {
+        let serial = Serial::new("31310420".to_string()).unwrap();
+        assert_eq!(format!("{serial:?}"), "Serial(***0420)");
+    }
+
     #[test]
     fn test_equality_and_hash() {
         let serial1 = Serial::new("31310420".to_string()).unwrap();
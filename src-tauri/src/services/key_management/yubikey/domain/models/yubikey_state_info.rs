@@ -22,4 +22,6 @@ pub struct YubiKeyStateInfo {
     pub has_tdes_protected_mgmt_key: bool,
     pub created_at: DateTime<Utc>,
     pub last_used: Option<DateTime<Utc>>,
+    /// Smart-card reader the device was found on
+    pub reader_name: Option<String>,
 }
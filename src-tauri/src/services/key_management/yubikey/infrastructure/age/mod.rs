@@ -3,6 +3,7 @@
 //! This module provides both standard and PTY-based implementations
 //! for YubiKey operations via age-plugin-yubikey.
 
+pub mod plugin_protocol;
 pub mod provider;
 pub mod provider_pty;
 pub mod pty_helpers;
@@ -0,0 +1,74 @@
+//! age-plugin-yubikey protocol probing
+//!
+//! Reads the plugin's advertised version so failed plugin runs can be
+//! classified as protocol mismatches instead of generic plugin failures.
+
+use crate::services::key_management::yubikey::domain::errors::YubiKeyError;
+use crate::services::key_management::yubikey::domain::models::{
+    PluginProtocolInfo, classify_plugin_failure,
+};
+use std::path::Path;
+use std::process::Stdio;
+use std::time::Duration;
+use tokio::process::Command;
+use tokio::time::timeout;
+use tracing::{debug, warn};
+
+// Windows-specific process creation flags to hide console windows
+#[cfg(target_os = "windows")]
+use std::os::windows::process::CommandExt;
+
+#[cfg(target_os = "windows")]
+const CREATE_NO_WINDOW: u32 = 0x08000000;
+
+/// `--version` never touches the device, so it should answer quickly
+const VERSION_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Run `age-plugin-yubikey --version` and return its output
+pub async fn read_plugin_version(plugin_path: &Path) -> Option<String> {
+    let mut cmd = Command::new(plugin_path);
+    cmd.arg("--version")
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+
+    #[cfg(target_os = "windows")]
+    cmd.creation_flags(CREATE_NO_WINDOW);
+
+    let output = match timeout(VERSION_TIMEOUT, cmd.output()).await {
+        Ok(Ok(output)) if output.status.success() => output,
+        Ok(Ok(output)) => {
+            debug!(status = ?output.status, "age-plugin-yubikey --version failed");
+            return None;
+        }
+        Ok(Err(e)) => {
+            debug!(error = %e, "Failed to run age-plugin-yubikey --version");
+            return None;
+        }
+        Err(_) => {
+            debug!("age-plugin-yubikey --version timed out");
+            return None;
+        }
+    };
+
+    let stdout = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (!stdout.is_empty()).then_some(stdout)
+}
+
+/// Advertised plugin version and the protocol it negotiates with our age crate
+pub async fn plugin_protocol_info(plugin_path: &Path) -> PluginProtocolInfo {
+    let version_output = read_plugin_version(plugin_path).await;
+    PluginProtocolInfo::from_version_output(version_output.as_deref())
+}
+
+/// Classify a failed plugin run, returning a protocol mismatch error if that
+/// is what the failure was
+pub async fn check_plugin_failure(plugin_path: &Path, stderr: &str) -> Option<YubiKeyError> {
+    let version_output = read_plugin_version(plugin_path).await;
+    let mismatch = classify_plugin_failure(version_output.as_deref(), stderr);
+
+    if let Some(error) = &mismatch {
+        warn!(error = %error, "age-plugin-yubikey protocol mismatch");
+    }
+
+    mismatch
+}
@@ -7,7 +7,9 @@
     AgeHeader, DataEncryptionKey, ProviderInfo, YubiIdentityProvider, YubiRecipient,
 };
 use super::super::pty::core::get_age_path;
+use super::plugin_protocol::check_plugin_failure;
 use crate::services::key_management::yubikey::domain::errors::{YubiKeyError, YubiKeyResult};
+use crate::types::Sensitive;
 use std::path::PathBuf;
 use std::process::Stdio;
 use std::time::Duration;
@@ -147,6 +149,9 @@ async fn execute_plugin(&self, args: &[&str]) -> YubiKeyResult<(String, String)>
         let stderr = String::from_utf8_lossy(&output.stderr).to_string();
 
         if !output.status.success() {
+            if let Some(mismatch) = check_plugin_failure(&self.plugin_path, &stderr).await {
+                return Err(mismatch);
+            }
             return Err(YubiKeyError::age_plugin(format!(
                 "age-plugin-yubikey failed: {stderr}"
             )));
@@ -218,9 +223,9 @@ pub(super) fn parse_recipients(&self, output: &str) -> YubiKeyResult<Vec<YubiRec
             };
 
             recipients.push(YubiRecipient {
-                recipient: recipient_str,
+                recipient: Sensitive::new(recipient_str),
                 label,
-                serial,
+                serial: Sensitive::new(serial),
                 slot,
             });
         }
@@ -4,6 +4,7 @@
 //! allowing multiple implementation strategies (age-plugin-yubikey, direct hardware, etc.)
 
 use crate::services::key_management::yubikey::domain::errors::{YubiKeyError, YubiKeyResult};
+use crate::types::Sensitive;
 use serde::{Deserialize, Serialize};
 use std::fmt::Debug;
 use zeroize::ZeroizeOnDrop;
@@ -12,11 +13,11 @@
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct YubiRecipient {
     /// age-compatible recipient string (e.g., "age1yubikey1...")
-    pub recipient: String,
+    pub recipient: Sensitive<String>,
     /// Human-readable label for the recipient
     pub label: String,
     /// YubiKey serial number
-    pub serial: String,
+    pub serial: Sensitive<String>,
     /// PIV slot number
     pub slot: u8,
 }
@@ -27,7 +28,7 @@ pub struct AgeHeader {
     /// Raw header data from age file
     pub data: Vec<u8>,
     /// Recipients that can decrypt this file
-    pub recipients: Vec<String>,
+    pub recipients: Vec<Sensitive<String>>,
 }
 
 /// Data encryption key (DEK) with secure memory handling
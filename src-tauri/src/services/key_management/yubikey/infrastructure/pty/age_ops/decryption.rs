@@ -4,8 +4,11 @@
 
 use super::super::core::{PtyError, Result, run_age_plugin_yubikey};
 use crate::prelude::*;
+use crate::services::shared::infrastructure::SecureDeleteService;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use zeroize::Zeroizing;
 
 // TODO: Cleanup after Windows testing - remove unused implementations
 // Platform-specific imports
@@ -18,6 +21,9 @@
 // Pipes implementation preserved but not used (for reference/rollback)
 // use decryption_helpers::run_age_decryption_pipes_windows;
 
+/// Distinguishes temporary files of concurrent sessions in one process
+static SESSION_COUNTER: AtomicU64 = AtomicU64::new(0);
+
 /// Decrypt data using age CLI with PTY for YubiKey interaction
 /// This function creates the necessary temporary files and handles the PTY interaction
 #[instrument(skip(encrypted_data, pin))]
@@ -29,101 +35,180 @@ pub fn decrypt_data_with_yubikey_pty(
     identity_tag: &str,
     pin: &str,
 ) -> Result<Vec<u8>> {
-    info!(
-        serial = %redact_serial(serial),
-        slot = slot,
-        recipient = %redact_key(recipient),
-        data_size = encrypted_data.len(),
-        "Starting YubiKey PTY decryption"
-    );
+    YubiKeyDecryptSession::open(serial, slot, recipient, identity_tag, pin)?.decrypt(encrypted_data)
+}
 
-    // Create temporary files
-    let temp_dir = std::env::temp_dir();
-    let process_id = std::process::id();
+/// One YubiKey unlock reused for a sequence of decryptions
+///
+/// The identity file is written once and the PIN is held (zeroized on drop)
+/// so every archive in a batch is decrypted without asking the user again.
+/// age runs one plugin process per input file, so a cached touch policy is
+/// what lets back-to-back decryptions share a single touch; the caller
+/// tracks the cache window and re-prompts when it lapses.
+pub struct YubiKeyDecryptSession {
+    serial: String,
+    pin: Zeroizing<String>,
+    identity_content: String,
+    temp_dir: PathBuf,
+    temp_identity: PathBuf,
+    session_id: u64,
+    decryptions: u32,
+}
 
-    let temp_encrypted = temp_dir.join(format!("yubikey_decrypt_{}.age", process_id));
-    let temp_identity = temp_dir.join(format!("yubikey_identity_{}.txt", process_id));
-    let temp_output = temp_dir.join(format!("yubikey_decrypt_{}.txt", process_id));
+impl std::fmt::Debug for YubiKeyDecryptSession {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("YubiKeyDecryptSession")
+            .field("serial", &redact_serial(&self.serial))
+            .field("decryptions", &self.decryptions)
+            .finish_non_exhaustive()
+    }
+}
 
-    // Write encrypted data to temporary file
-    fs::write(&temp_encrypted, encrypted_data).map_err(|e| {
-        error!(
-            error = %e,
-            temp_file = %temp_encrypted.display(),
-            "Failed to write encrypted data to temporary file"
+impl YubiKeyDecryptSession {
+    /// Write the identity file for a YubiKey slot and keep the PIN for reuse
+    pub fn open(
+        serial: &str,
+        slot: u8,
+        recipient: &str,
+        identity_tag: &str,
+        pin: &str,
+    ) -> Result<Self> {
+        info!(
+            serial = %redact_serial(serial),
+            slot = slot,
+            recipient = %redact_key(recipient),
+            "Opening YubiKey PTY decryption session"
         );
-        PtyError::Io(e)
-    })?;
 
-    // Create identity file content with proper format (matching POC)
-    let identity_content = format!(
-        "#       Serial: {}, Slot: {}\n#   PIN policy: cached\n# Touch policy: cached\n#    Recipient: {}\n{}\n",
-        serial, slot, recipient, identity_tag
-    );
+        let temp_dir = std::env::temp_dir();
+        let session_id = SESSION_COUNTER.fetch_add(1, Ordering::Relaxed);
+        let temp_identity = temp_dir.join(format!(
+            "yubikey_identity_{}_{}.txt",
+            std::process::id(),
+            session_id
+        ));
 
-    // Write identity file
-    fs::write(&temp_identity, identity_content).map_err(|e| {
-        error!(
-            error = %e,
-            temp_file = %temp_identity.display(),
-            "Failed to write identity file"
+        // Create identity file content with proper format (matching POC)
+        let identity_content = format!(
+            "#       Serial: {}, Slot: {}\n#   PIN policy: cached\n# Touch policy: cached\n#    Recipient: {}\n{}\n",
+            serial, slot, recipient, identity_tag
         );
-        // Clean up encrypted file
-        let _ = fs::remove_file(&temp_encrypted);
-        PtyError::Io(e)
-    })?;
-
-    debug!(
-        temp_encrypted = %temp_encrypted.display(),
-        temp_identity = %temp_identity.display(),
-        temp_output = %temp_output.display(),
-        "Created temporary files for YubiKey decryption"
-    );
 
-    // TODO: Cleanup after Windows testing - finalize which implementation to keep
-    // Run age CLI - Platform-specific approach
-    // Windows: PTY with ANSI stripping + timing fallback
-    // macOS/Linux: Standard PTY (working correctly)
-    #[cfg(target_os = "windows")]
-    let result = run_age_decryption_pty_windows(&temp_encrypted, &temp_identity, &temp_output, pin);
+        fs::write(&temp_identity, &identity_content).map_err(|e| {
+            error!(
+                error = %e,
+                temp_file = %temp_identity.display(),
+                "Failed to write identity file"
+            );
+            PtyError::Io(e)
+        })?;
+
+        Ok(Self {
+            serial: serial.to_string(),
+            pin: Zeroizing::new(pin.to_string()),
+            identity_content,
+            temp_identity,
+            temp_dir,
+            session_id,
+            decryptions: 0,
+        })
+    }
 
-    #[cfg(not(target_os = "windows"))]
-    let result = run_age_decryption_pty(&temp_encrypted, &temp_identity, &temp_output, pin);
+    /// Number of decryptions attempted in this session
+    pub fn decryptions(&self) -> u32 {
+        self.decryptions
+    }
 
-    // Pipes implementation preserved for reference (not currently used)
-    // let result = run_age_decryption_pipes_windows(...);
+    /// Decrypt one archive with the session's identity and PIN
+    #[instrument(skip_all, fields(data_size = encrypted_data.len()))]
+    pub fn decrypt(&mut self, encrypted_data: &[u8]) -> Result<Vec<u8>> {
+        self.decryptions += 1;
+        let stem = format!(
+            "yubikey_decrypt_{}_{}_{}",
+            std::process::id(),
+            self.session_id,
+            self.decryptions
+        );
 
-    // Clean up input files
-    let _ = fs::remove_file(&temp_encrypted);
-    let _ = fs::remove_file(&temp_identity);
+        // A stalled child's watchdog cleanup removes the identity file too
+        if !self.temp_identity.exists() {
+            fs::write(&self.temp_identity, &self.identity_content).map_err(PtyError::Io)?;
+        }
+        let temp_encrypted = self.temp_dir.join(format!("{stem}.age"));
+        let temp_output = self.temp_dir.join(format!("{stem}.txt"));
 
-    match result {
-        Ok(()) => {
-            // Read the decrypted output from the file
-            let decrypted_data = fs::read(&temp_output).map_err(|e| {
+        // Write encrypted data to temporary file
+        fs::write(&temp_encrypted, encrypted_data).map_err(|e| {
+            error!(
+                error = %e,
+                temp_file = %temp_encrypted.display(),
+                "Failed to write encrypted data to temporary file"
+            );
+            PtyError::Io(e)
+        })?;
+
+        debug!(
+            temp_encrypted = %temp_encrypted.display(),
+            temp_identity = %self.temp_identity.display(),
+            temp_output = %temp_output.display(),
+            decryption = self.decryptions,
+            "Created temporary files for YubiKey decryption"
+        );
+
+        // TODO: Cleanup after Windows testing - finalize which implementation to keep
+        // Run age CLI - Platform-specific approach
+        // Windows: PTY with ANSI stripping + timing fallback
+        // macOS/Linux: Standard PTY (working correctly)
+        #[cfg(target_os = "windows")]
+        let result = run_age_decryption_pty_windows(
+            &temp_encrypted,
+            &self.temp_identity,
+            &temp_output,
+            &self.pin,
+        );
+
+        #[cfg(not(target_os = "windows"))]
+        let result = run_age_decryption_pty(
+            &temp_encrypted,
+            &self.temp_identity,
+            &temp_output,
+            &self.pin,
+        );
+
+        // Pipes implementation preserved for reference (not currently used)
+        // let result = run_age_decryption_pipes_windows(...);
+
+        let _ = SecureDeleteService::new().delete_file(&temp_encrypted);
+
+        let decrypted = result.and_then(|()| {
+            fs::read(&temp_output).map_err(|e| {
                 error!(
                     error = %e,
                     temp_output = %temp_output.display(),
                     "Failed to read decrypted output file"
                 );
                 PtyError::Io(e)
-            })?;
+            })
+        });
+        let _ = SecureDeleteService::new().delete_file(&temp_output);
 
-            // Clean up output file
-            let _ = fs::remove_file(&temp_output);
-
-            debug!(
-                encrypted_size = encrypted_data.len(),
-                decrypted_size = decrypted_data.len(),
-                "YubiKey PTY decryption completed successfully"
-            );
+        let decrypted_data = decrypted?;
+        debug!(
+            encrypted_size = encrypted_data.len(),
+            decrypted_size = decrypted_data.len(),
+            "YubiKey PTY decryption completed successfully"
+        );
+        Ok(decrypted_data)
+    }
+}
 
-            Ok(decrypted_data)
-        }
-        Err(e) => {
-            // Clean up output file
-            let _ = fs::remove_file(&temp_output);
-            Err(e)
+impl Drop for YubiKeyDecryptSession {
+    fn drop(&mut self) {
+        // The watchdog may already have removed it if a child stalled
+        if self.temp_identity.exists()
+            && let Err(e) = SecureDeleteService::new().delete_file(&self.temp_identity)
+        {
+            warn!(error = %e, "Failed to remove YubiKey identity file");
         }
     }
 }
@@ -168,7 +253,7 @@ pub fn decrypt_with_age_pty(
     let result = run_age_plugin_yubikey(args, Some(pin), true);
 
     // Clean up temp identity file
-    let _ = fs::remove_file(&temp_identity);
+    let _ = SecureDeleteService::new().delete_file(&temp_identity);
 
     result?;
 
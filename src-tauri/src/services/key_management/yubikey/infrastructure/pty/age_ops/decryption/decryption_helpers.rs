@@ -2,7 +2,11 @@
 use super::super::super::core::{
     COMMAND_TIMEOUT, PIN_INJECT_DELAY, PTY_COLS, PTY_ROWS, PtyError, PtyState, Result, get_age_path,
 };
+use crate::logging::in_current_trace;
 use crate::prelude::*;
+use crate::services::key_management::yubikey::infrastructure::pty::session_registry::{
+    PtySessionRegistry, command_label,
+};
 use crate::services::key_management::yubikey::infrastructure::pty::yubikey_prompt_patterns;
 use std::path::Path;
 
@@ -71,6 +75,18 @@ pub(super) fn run_age_decryption_pty(
 
     debug!("Age CLI process spawned successfully");
 
+    // Temporary files are removed if the watchdog has to kill the session
+    let session = PtySessionRegistry::global().register(
+        &command_label(&age_path),
+        child.clone_killer(),
+        vec![
+            identity_file.to_path_buf(),
+            encrypted_file.to_path_buf(),
+            output_file.to_path_buf(),
+        ],
+    );
+    let activity = session.session();
+
     let (tx, rx) = mpsc::channel::<PtyState>();
 
     // Reader thread for PTY output
@@ -80,7 +96,7 @@ pub(super) fn run_age_decryption_pty(
         .map_err(|e| PtyError::PtyOperation(format!("Failed to clone reader: {e}")))?;
 
     let tx_reader = tx.clone();
-    thread::spawn(move || {
+    thread::spawn(in_current_trace(move || {
         use std::io::Read;
 
         let mut raw_buffer = [0u8; 256];
@@ -97,6 +113,7 @@ pub(super) fn run_age_decryption_pty(
 
                     // Convert to string and accumulate
                     if let Ok(text) = std::str::from_utf8(raw_data) {
+                        activity.record_output(text);
                         accumulated_output.push_str(text);
                         debug!(raw_text = %text, "Raw age CLI output");
 
@@ -157,7 +174,7 @@ pub(super) fn run_age_decryption_pty(
             }
         }
         debug!("PTY reader thread exiting");
-    });
+    }));
 
     let mut writer = pair
         .master
@@ -170,6 +187,8 @@ pub(super) fn run_age_decryption_pty(
     info!("🔐 Touch your YubiKey when prompted to complete decryption!");
 
     loop {
+        session.check()?;
+
         if start.elapsed() > COMMAND_TIMEOUT {
             warn!("Operation timed out");
             let _ = child.kill();
@@ -205,6 +224,7 @@ pub(super) fn run_age_decryption_pty(
                 // Check if process has exited
                 match child.try_wait() {
                     Ok(Some(status)) => {
+                        session.check()?;
                         debug!(status = ?status, "Process exited");
                         if status.success() {
                             info!("Age decryption completed successfully");
@@ -231,6 +251,7 @@ pub(super) fn run_age_decryption_pty(
     }
 
     let _ = child.wait();
+    session.check()?;
     info!("Age CLI decryption process completed");
     Ok(())
 }
@@ -310,6 +331,18 @@ pub(super) fn run_age_decryption_pty_windows(
 
     debug!("Age CLI process spawned successfully (Windows PTY)");
 
+    // Temporary files are removed if the watchdog has to kill the session
+    let session = PtySessionRegistry::global().register(
+        &command_label(&age_path),
+        child.clone_killer(),
+        vec![
+            identity_file.to_path_buf(),
+            encrypted_file.to_path_buf(),
+            output_file.to_path_buf(),
+        ],
+    );
+    let activity = session.session();
+
     let (tx, rx) = mpsc::channel::<PtyState>();
 
     // Reader thread with ANSI stripping and raw byte logging
@@ -319,7 +352,7 @@ pub(super) fn run_age_decryption_pty_windows(
         .map_err(|e| PtyError::PtyOperation(format!("Failed to clone reader: {e}")))?;
 
     let tx_reader = tx.clone();
-    thread::spawn(move || {
+    thread::spawn(in_current_trace(move || {
         use std::io::Read;
 
         let mut raw_buffer = [0u8; 4096]; // Larger buffer for Windows
@@ -353,6 +386,7 @@ pub(super) fn run_age_decryption_pty_windows(
 
                     // Convert to string and log before stripping
                     if let Ok(text) = std::str::from_utf8(raw_data) {
+                        activity.record_output(text);
                         accumulated_output.push_str(text);
                         accumulated_raw.extend_from_slice(raw_data);
                         debug!(raw_text = %text, "Raw PTY text before stripping (Windows)");
@@ -416,7 +450,7 @@ pub(super) fn run_age_decryption_pty_windows(
             accumulated_raw_len = accumulated_raw.len(),
             "PTY reader thread exiting (Windows)"
         );
-    });
+    }));
 
     let mut writer = pair
         .master
@@ -430,6 +464,8 @@ pub(super) fn run_age_decryption_pty_windows(
     info!("🔐 Touch your YubiKey when prompted to complete decryption!");
 
     loop {
+        session.check()?;
+
         if start.elapsed() > COMMAND_TIMEOUT {
             warn!("Operation timed out (Windows PTY)");
             let _ = child.kill();
@@ -499,6 +535,7 @@ pub(super) fn run_age_decryption_pty_windows(
                 // Check if process has exited
                 match child.try_wait() {
                     Ok(Some(status)) => {
+                        session.check()?;
                         debug!(status = ?status, "Process exited (Windows)");
                         if status.success() {
                             info!("Age decryption completed successfully (Windows PTY)");
@@ -525,6 +562,7 @@ pub(super) fn run_age_decryption_pty_windows(
     }
 
     let _ = child.wait();
+    session.check()?;
     info!("Age CLI decryption process completed (Windows PTY)");
     Ok(())
 }
@@ -617,7 +655,7 @@ pub(super) fn run_age_decryption_pipes_windows(
         .ok_or_else(|| PtyError::PtyOperation("Failed to get stderr".to_string()))?;
 
     let tx_stderr = tx.clone();
-    thread::spawn(move || {
+    thread::spawn(in_current_trace(move || {
         use std::io::Read;
 
         let mut stderr = stderr;
@@ -671,7 +709,7 @@ pub(super) fn run_age_decryption_pipes_windows(
             accumulated_length = accumulated.len(),
             "Stderr reader thread exiting"
         );
-    });
+    }));
 
     // Stdout reader thread - just consume output (actual decrypted data goes to file)
     let stdout = child
@@ -679,7 +717,7 @@ pub(super) fn run_age_decryption_pipes_windows(
         .take()
         .ok_or_else(|| PtyError::PtyOperation("Failed to get stdout".to_string()))?;
 
-    thread::spawn(move || {
+    thread::spawn(in_current_trace(move || {
         let reader = BufReader::new(stdout);
         for line in reader.lines() {
             if let Ok(line) = line {
@@ -689,7 +727,7 @@ pub(super) fn run_age_decryption_pipes_windows(
             }
         }
         debug!("Stdout reader thread exiting");
-    });
+    }));
 
     let start = Instant::now();
     let mut pin_sent = false;
@@ -1,5 +1,7 @@
 /// Core PTY functionality for YubiKey operations
 /// Provides low-level PTY command execution
+use super::session_registry::{PtySessionRegistry, command_label};
+use crate::logging::in_current_trace;
 use crate::prelude::*;
 use portable_pty::{CommandBuilder, PtySize, native_pty_system};
 use std::io::{BufRead, BufReader, Write};
@@ -31,6 +33,15 @@ pub enum PtyError {
     #[error("Touch timeout - YubiKey was not touched within timeout period")]
     TouchTimeout,
 
+    #[error("PTY session {session_id} stalled after {idle_secs} seconds without output")]
+    SessionStalled { session_id: String, idle_secs: u64 },
+
+    #[error("PTY session {0} was killed")]
+    SessionKilled(String),
+
+    #[error("PTY session not found: {0}")]
+    SessionNotFound(String),
+
     #[error("IO error: {0}")]
     Io(#[from] std::io::Error),
 }
@@ -163,6 +174,13 @@ pub fn run_age_plugin_yubikey(
         PtyError::PtyOperation(format!("Failed to spawn command: {e}"))
     })?;
 
+    let session = PtySessionRegistry::global().register(
+        &command_label(&age_path),
+        child.clone_killer(),
+        Vec::new(),
+    );
+    let activity = session.session();
+
     let (tx, rx) = mpsc::channel::<PtyState>();
 
     // Reader thread
@@ -172,7 +190,7 @@ pub fn run_age_plugin_yubikey(
         .map_err(|e| PtyError::PtyOperation(format!("Failed to clone reader: {e}")))?;
 
     let tx_reader = tx.clone();
-    thread::spawn(move || {
+    thread::spawn(in_current_trace(move || {
         let mut buf_reader = BufReader::new(reader);
         let mut buffer = String::new();
         let mut output = String::new();
@@ -185,6 +203,7 @@ pub fn run_age_plugin_yubikey(
                     break;
                 }
                 Ok(_n) => {
+                    activity.record_output(&buffer);
                     let line = buffer.trim();
                     output.push_str(&buffer);
 
@@ -217,7 +236,7 @@ pub fn run_age_plugin_yubikey(
         }
 
         let _ = tx_reader.send(PtyState::Complete(output));
-    });
+    }));
 
     let mut writer = pair
         .master
@@ -229,6 +248,8 @@ pub fn run_age_plugin_yubikey(
     let mut result = String::new();
 
     loop {
+        session.check()?;
+
         if start.elapsed() > COMMAND_TIMEOUT {
             let _ = child.kill();
             return Err(PtyError::Timeout(COMMAND_TIMEOUT.as_secs()));
@@ -278,6 +299,7 @@ pub fn run_age_plugin_yubikey(
                 // Check if process has exited
                 match child.try_wait() {
                     Ok(Some(status)) => {
+                        session.check()?;
                         if !status.success() {
                             return Err(PtyError::PtyOperation("Command failed".to_string()));
                         }
@@ -291,6 +313,7 @@ pub fn run_age_plugin_yubikey(
     }
 
     let _ = child.wait();
+    session.check()?;
     info!(
         result_length = result.len(),
         "age-plugin-yubikey command completed"
@@ -353,6 +376,13 @@ pub fn run_age_plugin_yubikey_windows(
         PtyError::PtyOperation(format!("Failed to spawn command: {e}"))
     })?;
 
+    let session = PtySessionRegistry::global().register(
+        &command_label(&age_path),
+        child.clone_killer(),
+        Vec::new(),
+    );
+    let activity = session.session();
+
     let (tx, rx) = mpsc::channel::<PtyState>();
 
     // Reader thread with raw read for DSR detection and ANSI stripping
@@ -362,7 +392,7 @@ pub fn run_age_plugin_yubikey_windows(
         .map_err(|e| PtyError::PtyOperation(format!("Failed to clone reader: {e}")))?;
 
     let tx_reader = tx.clone();
-    thread::spawn(move || {
+    thread::spawn(in_current_trace(move || {
         let mut reader = reader;
         let mut raw_buffer = [0u8; 4096];
         let mut clean_output = String::new(); // Stripped text for parser (accumulated from chunks)
@@ -389,6 +419,7 @@ pub fn run_age_plugin_yubikey_windows(
                     // Strip ANSI from THIS chunk only
                     let chunk_stripped = strip_ansi_escapes::strip(raw_data);
                     if let Ok(chunk_clean) = String::from_utf8(chunk_stripped) {
+                        activity.record_output(&chunk_clean);
                         clean_output.push_str(&chunk_clean); // APPEND!
 
                         // Check patterns in this chunk
@@ -426,7 +457,7 @@ pub fn run_age_plugin_yubikey_windows(
             "Reader thread exiting"
         );
         let _ = tx_reader.send(PtyState::Complete(clean_output));
-    });
+    }));
 
     let mut writer = pair
         .master
@@ -438,6 +469,8 @@ pub fn run_age_plugin_yubikey_windows(
     let mut result = String::new();
 
     loop {
+        session.check()?;
+
         if start.elapsed() > COMMAND_TIMEOUT {
             let _ = child.kill();
             return Err(PtyError::Timeout(COMMAND_TIMEOUT.as_secs()));
@@ -500,6 +533,7 @@ pub fn run_age_plugin_yubikey_windows(
             },
             Err(mpsc::RecvTimeoutError::Timeout) => match child.try_wait() {
                 Ok(Some(status)) => {
+                    session.check()?;
                     if !status.success() {
                         return Err(PtyError::PtyOperation(
                             "Command failed (Windows)".to_string(),
@@ -514,6 +548,7 @@ pub fn run_age_plugin_yubikey_windows(
     }
 
     let _ = child.wait();
+    session.check()?;
     info!(
         result_length = result.len(),
         "age-plugin-yubikey command completed (Windows)"
@@ -574,6 +609,12 @@ fn run_ykman_pty(args: Vec<String>, pin: Option<&str>) -> Result<String> {
         .spawn_command(cmd)
         .map_err(|e| PtyError::PtyOperation(format!("Failed to spawn command: {e}")))?;
 
+    let session = PtySessionRegistry::global().register(
+        &command_label(&get_ykman_path()),
+        child.clone_killer(),
+        Vec::new(),
+    );
+
     // Similar PTY handling as age-plugin-yubikey
     // but adapted for ykman's output patterns
 
@@ -589,7 +630,12 @@ fn run_ykman_pty(args: Vec<String>, pin: Option<&str>) -> Result<String> {
         .map_err(|e| PtyError::PtyOperation(format!("Failed to take writer: {e}")))?;
 
     for line in reader.lines() {
-        let line = line?;
+        // A session killed by the watchdog surfaces as a read error
+        let line = line.map_err(|e| match session.check() {
+            Err(terminated) => terminated,
+            Ok(()) => PtyError::Io(e),
+        })?;
+        session.record_output(&line);
         debug!(output = %line, "ykman output line");
         output.push_str(&line);
         output.push('\n');
@@ -603,6 +649,7 @@ fn run_ykman_pty(args: Vec<String>, pin: Option<&str>) -> Result<String> {
     }
 
     let status = child.wait()?;
+    session.check()?;
     if !status.success() {
         error!(output_length = output.len(), "ykman command failed");
         return Err(PtyError::PtyOperation(format!("ykman failed: {output}")));
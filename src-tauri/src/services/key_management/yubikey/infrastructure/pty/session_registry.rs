@@ -0,0 +1,496 @@
+/// PTY session lifecycle management
+///
+/// Every PTY child is registered here for the duration of its operation so
+/// wedged sessions can be found and recovered without restarting the app.
+/// A watchdog thread kills sessions that stop producing output, removes their
+/// temporary files, and the originating operation then fails with
+/// `PtyError::SessionStalled`. Sessions whose last prompt asked for a touch
+/// get a longer threshold since the user may simply be slow.
+use super::core::{PtyError, Result};
+use super::yubikey_prompt_patterns;
+use crate::prelude::*;
+use crate::services::shared::infrastructure::SecureDeleteService;
+use crate::types::DurationMs;
+use chrono::{DateTime, Utc};
+use portable_pty::ChildKiller;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Silence allowed before a session is considered stalled
+pub const PTY_STALL_THRESHOLD: Duration = Duration::from_secs(20);
+/// Silence allowed while waiting for the user to touch the YubiKey
+pub const PTY_TOUCH_STALL_THRESHOLD: Duration = Duration::from_secs(50);
+/// How often the watchdog checks registered sessions
+pub const PTY_WATCHDOG_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Stall thresholds used by the watchdog
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WatchdogConfig {
+    pub stall_threshold: Duration,
+    pub touch_stall_threshold: Duration,
+}
+
+impl Default for WatchdogConfig {
+    fn default() -> Self {
+        Self {
+            stall_threshold: PTY_STALL_THRESHOLD,
+            touch_stall_threshold: PTY_TOUCH_STALL_THRESHOLD,
+        }
+    }
+}
+
+/// Why a session was ended from outside its operation
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SessionTermination {
+    /// No output for longer than the stall threshold
+    Stalled { idle: Duration },
+    /// Killed on request via `kill_pty_session`
+    Killed,
+}
+
+/// Diagnostic snapshot of a registered session
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct PtySessionInfo {
+    pub id: String,
+    /// Program name (arguments are omitted; they may contain paths)
+    pub command: String,
+    pub started_at: DateTime<Utc>,
+    pub last_output_at: DateTime<Utc>,
+    pub idle_ms: DurationMs,
+    pub waiting_for_touch: bool,
+    /// True once the session was stalled or killed and is being torn down
+    pub terminated: bool,
+}
+
+#[derive(Debug)]
+struct SessionState {
+    last_output: Instant,
+    last_output_at: DateTime<Utc>,
+    waiting_for_touch: bool,
+    termination: Option<SessionTermination>,
+    killer: Box<dyn ChildKiller + Send + Sync>,
+    cleanup_paths: Vec<PathBuf>,
+}
+
+/// A registered PTY child
+#[derive(Debug)]
+pub struct PtySession {
+    id: String,
+    command: String,
+    started_at: DateTime<Utc>,
+    state: Mutex<SessionState>,
+}
+
+impl PtySession {
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// Note output from the child; the latest prompt decides the touch-wait state
+    pub fn record_output(&self, text: &str) {
+        self.record_output_at(text, Instant::now());
+    }
+
+    fn record_output_at(&self, text: &str, now: Instant) {
+        let text = text.trim();
+        if text.is_empty() {
+            return;
+        }
+
+        let mut state = self.lock_state();
+        state.last_output = now;
+        state.last_output_at = Utc::now();
+        state.waiting_for_touch = yubikey_prompt_patterns::is_touch_prompt(text);
+    }
+
+    /// Fail if the session was stalled or killed from outside the operation
+    pub fn check(&self) -> Result<()> {
+        match self.lock_state().termination {
+            None => Ok(()),
+            Some(SessionTermination::Stalled { idle }) => Err(PtyError::SessionStalled {
+                session_id: self.id.clone(),
+                idle_secs: idle.as_secs(),
+            }),
+            Some(SessionTermination::Killed) => Err(PtyError::SessionKilled(self.id.clone())),
+        }
+    }
+
+    fn info(&self, now: Instant) -> PtySessionInfo {
+        let state = self.lock_state();
+        PtySessionInfo {
+            id: self.id.clone(),
+            command: self.command.clone(),
+            started_at: self.started_at,
+            last_output_at: state.last_output_at,
+            idle_ms: now.saturating_duration_since(state.last_output).into(),
+            waiting_for_touch: state.waiting_for_touch,
+            terminated: state.termination.is_some(),
+        }
+    }
+
+    /// Idle time if it exceeds the applicable threshold
+    fn stalled_for(&self, config: &WatchdogConfig, now: Instant) -> Option<Duration> {
+        let state = self.lock_state();
+        if state.termination.is_some() {
+            return None;
+        }
+
+        let idle = now.saturating_duration_since(state.last_output);
+        let threshold = if state.waiting_for_touch {
+            config.touch_stall_threshold
+        } else {
+            config.stall_threshold
+        };
+
+        (idle > threshold).then_some(idle)
+    }
+
+    /// Kill the child and remove its temporary files
+    ///
+    /// Fails only if the child couldn't be killed; the files are removed
+    /// either way. Terminating an ended session does nothing.
+    fn terminate(&self, reason: SessionTermination) -> std::io::Result<()> {
+        let mut state = self.lock_state();
+        if state.termination.is_some() {
+            return Ok(());
+        }
+        state.termination = Some(reason);
+
+        let killed = state.killer.kill();
+        if let Err(e) = &killed {
+            warn!(session_id = %self.id, error = %e, "Failed to kill PTY session");
+        }
+
+        for path in state.cleanup_paths.drain(..) {
+            if path.exists()
+                && let Err(e) = SecureDeleteService::new().delete_file(&path)
+            {
+                warn!(
+                    session_id = %self.id,
+                    path = %path.display(),
+                    error = %e,
+                    "Failed to remove PTY session temporary file"
+                );
+            }
+        }
+        killed
+    }
+
+    fn lock_state(&self) -> std::sync::MutexGuard<'_, SessionState> {
+        // A poisoned lock only means a reader thread panicked; the state is still usable
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Registration of a running session; unregisters on drop
+#[derive(Debug)]
+pub struct PtySessionGuard {
+    session: Arc<PtySession>,
+    sessions: Arc<Mutex<HashMap<String, Arc<PtySession>>>>,
+}
+
+impl PtySessionGuard {
+    /// Shared handle for reader threads
+    pub fn session(&self) -> Arc<PtySession> {
+        Arc::clone(&self.session)
+    }
+
+    pub fn check(&self) -> Result<()> {
+        self.session.check()
+    }
+}
+
+impl std::ops::Deref for PtySessionGuard {
+    type Target = PtySession;
+
+    fn deref(&self) -> &PtySession {
+        &self.session
+    }
+}
+
+impl Drop for PtySessionGuard {
+    fn drop(&mut self) {
+        if let Ok(mut sessions) = self.sessions.lock() {
+            sessions.remove(&self.session.id);
+        }
+    }
+}
+
+/// Registry of active PTY sessions
+#[derive(Debug, Default)]
+pub struct PtySessionRegistry {
+    sessions: Arc<Mutex<HashMap<String, Arc<PtySession>>>>,
+    config: Mutex<WatchdogConfig>,
+    next_id: AtomicU64,
+}
+
+impl PtySessionRegistry {
+    pub fn new(config: WatchdogConfig) -> Self {
+        Self {
+            config: Mutex::new(config),
+            ..Default::default()
+        }
+    }
+
+    /// Process-wide registry; starts the watchdog thread on first use
+    pub fn global() -> &'static PtySessionRegistry {
+        static REGISTRY: OnceLock<PtySessionRegistry> = OnceLock::new();
+        static WATCHDOG: OnceLock<()> = OnceLock::new();
+
+        let registry = REGISTRY.get_or_init(|| PtySessionRegistry::new(WatchdogConfig::default()));
+        WATCHDOG.get_or_init(|| {
+            thread::spawn(|| {
+                loop {
+                    thread::sleep(PTY_WATCHDOG_INTERVAL);
+                    PtySessionRegistry::global().sweep(Instant::now());
+                }
+            });
+        });
+        registry
+    }
+
+    pub fn config(&self) -> WatchdogConfig {
+        *self.config.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Change the stall thresholds for subsequent watchdog sweeps
+    pub fn set_config(&self, config: WatchdogConfig) {
+        *self.config.lock().unwrap_or_else(|e| e.into_inner()) = config;
+    }
+
+    /// Register a spawned child
+    ///
+    /// `cleanup_paths` are removed if the session is stalled or killed
+    /// (temporary identity files and the like).
+    pub fn register(
+        &self,
+        command: &str,
+        killer: Box<dyn ChildKiller + Send + Sync>,
+        cleanup_paths: Vec<PathBuf>,
+    ) -> PtySessionGuard {
+        let id = format!(
+            "pty-{}-{}",
+            std::process::id(),
+            self.next_id.fetch_add(1, Ordering::Relaxed) + 1
+        );
+        let now = Utc::now();
+        let session = Arc::new(PtySession {
+            id: id.clone(),
+            command: command.to_string(),
+            started_at: now,
+            state: Mutex::new(SessionState {
+                last_output: Instant::now(),
+                last_output_at: now,
+                waiting_for_touch: false,
+                termination: None,
+                killer,
+                cleanup_paths,
+            }),
+        });
+
+        debug!(session_id = %id, command = %command, "Registered PTY session");
+        self.lock_sessions().insert(id, Arc::clone(&session));
+
+        PtySessionGuard {
+            session,
+            sessions: Arc::clone(&self.sessions),
+        }
+    }
+
+    /// Snapshot of all registered sessions, oldest first
+    pub fn list(&self) -> Vec<PtySessionInfo> {
+        let now = Instant::now();
+        let mut sessions: Vec<PtySessionInfo> =
+            self.lock_sessions().values().map(|s| s.info(now)).collect();
+        sessions.sort_by_key(|s| s.started_at);
+        sessions
+    }
+
+    /// Kill a session for manual recovery
+    pub fn kill(&self, id: &str) -> Result<()> {
+        let session = self
+            .lock_sessions()
+            .get(id)
+            .cloned()
+            .ok_or_else(|| PtyError::SessionNotFound(id.to_string()))?;
+
+        warn!(session_id = %id, "Killing PTY session on request");
+        let _ = session.terminate(SessionTermination::Killed);
+        Ok(())
+    }
+
+    /// Kill every live session; returns each one's ID and whether it died
+    pub fn kill_all(&self) -> Vec<(String, std::io::Result<()>)> {
+        let sessions: Vec<Arc<PtySession>> = self.lock_sessions().values().cloned().collect();
+
+        sessions
+            .into_iter()
+            .filter(|session| session.lock_state().termination.is_none())
+            .map(|session| {
+                warn!(session_id = %session.id, "Killing PTY session");
+                let killed = session.terminate(SessionTermination::Killed);
+                (session.id.clone(), killed)
+            })
+            .collect()
+    }
+
+    /// Terminate sessions silent past their threshold; returns their IDs
+    pub fn sweep(&self, now: Instant) -> Vec<String> {
+        let config = self.config();
+        let sessions: Vec<Arc<PtySession>> = self.lock_sessions().values().cloned().collect();
+
+        let mut stalled = Vec::new();
+        for session in sessions {
+            if let Some(idle) = session.stalled_for(&config, now) {
+                warn!(
+                    session_id = %session.id,
+                    command = %session.command,
+                    idle_secs = idle.as_secs(),
+                    "PTY session stalled, terminating"
+                );
+                let _ = session.terminate(SessionTermination::Stalled { idle });
+                stalled.push(session.id.clone());
+            }
+        }
+        stalled
+    }
+
+    fn lock_sessions(&self) -> std::sync::MutexGuard<'_, HashMap<String, Arc<PtySession>>> {
+        self.sessions.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Program name for display, without directories or arguments
+pub fn command_label(program: &std::path::Path) -> String {
+    program
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| program.to_string_lossy().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicBool;
+    use tempfile::TempDir;
+
+    /// Stand-in for a child that stopped producing output
+    #[derive(Debug, Clone, Default)]
+    struct FakeChild {
+        killed: Arc<AtomicBool>,
+    }
+
+    impl ChildKiller for FakeChild {
+        fn kill(&mut self) -> std::io::Result<()> {
+            self.killed.store(true, Ordering::SeqCst);
+            Ok(())
+        }
+
+        fn clone_killer(&self) -> Box<dyn ChildKiller + Send + Sync> {
+            Box::new(self.clone())
+        }
+    }
+
+    fn registry() -> PtySessionRegistry {
+        PtySessionRegistry::new(WatchdogConfig {
+            stall_threshold: Duration::from_secs(5),
+            touch_stall_threshold: Duration::from_secs(30),
+        })
+    }
+
+    #[test]
+    fn test_silent_session_is_killed_and_cleaned_up() {
+        let temp = TempDir::new().unwrap();
+        let identity = temp.path().join("identity.txt");
+        std::fs::write(&identity, "AGE-PLUGIN-YUBIKEY-TEST").unwrap();
+
+        let registry = registry();
+        let child = FakeChild::default();
+        let guard = registry.register("age", Box::new(child.clone()), vec![identity.clone()]);
+        let start = Instant::now();
+        guard.record_output_at("Enter PIN for YubiKey", start);
+
+        assert!(registry.sweep(start + Duration::from_secs(4)).is_empty());
+        assert!(guard.check().is_ok());
+
+        let stalled = registry.sweep(start + Duration::from_secs(6));
+        assert_eq!(stalled, vec![guard.id().to_string()]);
+        assert!(child.killed.load(Ordering::SeqCst));
+        assert!(!identity.exists());
+        assert!(matches!(
+            guard.check(),
+            Err(PtyError::SessionStalled { idle_secs: 6, .. })
+        ));
+        assert!(registry.list()[0].terminated);
+    }
+
+    #[test]
+    fn test_touch_wait_gets_longer_threshold() {
+        let registry = registry();
+        let child = FakeChild::default();
+        let guard = registry.register("age", Box::new(child.clone()), vec![]);
+        let start = Instant::now();
+        guard.record_output_at("Touch your YubiKey", start);
+
+        assert!(registry.sweep(start + Duration::from_secs(20)).is_empty());
+        assert!(!child.killed.load(Ordering::SeqCst));
+        assert!(registry.list()[0].waiting_for_touch);
+
+        assert_eq!(registry.sweep(start + Duration::from_secs(31)).len(), 1);
+        assert!(child.killed.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn test_output_after_touch_prompt_clears_touch_wait() {
+        let registry = registry();
+        let guard = registry.register("age", Box::new(FakeChild::default()), vec![]);
+        let start = Instant::now();
+        guard.record_output_at("Touch your YubiKey", start);
+        guard.record_output_at("Generating key...", start);
+
+        assert_eq!(registry.sweep(start + Duration::from_secs(6)).len(), 1);
+    }
+
+    #[test]
+    fn test_manual_kill_and_unregister_on_drop() {
+        let registry = registry();
+        let child = FakeChild::default();
+        let guard = registry.register("ykman", Box::new(child.clone()), vec![]);
+        let id = guard.id().to_string();
+
+        assert_eq!(registry.list().len(), 1);
+        registry.kill(&id).unwrap();
+        assert!(child.killed.load(Ordering::SeqCst));
+        assert!(matches!(guard.check(), Err(PtyError::SessionKilled(_))));
+
+        drop(guard);
+        assert!(registry.list().is_empty());
+        assert!(matches!(
+            registry.kill(&id),
+            Err(PtyError::SessionNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_kill_all_skips_ended_sessions() {
+        let registry = registry();
+        let first = FakeChild::default();
+        let second = FakeChild::default();
+        let a = registry.register("age", Box::new(first.clone()), vec![]);
+        let b = registry.register("ykman", Box::new(second.clone()), vec![]);
+        registry.kill(a.id()).unwrap();
+        first.killed.store(false, Ordering::SeqCst);
+
+        let killed = registry.kill_all();
+        assert_eq!(killed.len(), 1);
+        assert_eq!(killed[0].0, b.id());
+        assert!(killed[0].1.is_ok());
+        assert!(second.killed.load(Ordering::SeqCst));
+        assert!(!first.killed.load(Ordering::SeqCst));
+        assert!(registry.kill_all().is_empty());
+    }
+}
@@ -39,6 +39,38 @@ pub fn has_default_pin(serial: &str) -> Result<bool> {
     Ok(has_default)
 }
 
+/// Read how many PIN attempts the YubiKey has left before the PIN blocks
+/// Uses 'ykman piv info', which reports the counter without spending an attempt
+#[instrument]
+pub fn get_pin_retries(serial: &str) -> Result<u8> {
+    let args = vec![
+        "--device".to_string(),
+        serial.to_string(),
+        "piv".to_string(),
+        "info".to_string(),
+    ];
+
+    let output = run_ykman_command(args, None)?;
+    let retries = parse_pin_retries(&output).ok_or_else(|| {
+        PtyError::PinFailed("PIN tries remaining not found in piv info".to_string())
+    })?;
+
+    debug!(serial = serial, retries, "Read PIN retry counter");
+    Ok(retries)
+}
+
+/// Remaining tries from a "PIN tries remaining: 2/3" line
+fn parse_pin_retries(output: &str) -> Option<u8> {
+    output.lines().find_map(|line| {
+        let (field, value) = line.split_once(':')?;
+        if field.trim() != "PIN tries remaining" {
+            return None;
+        }
+        let remaining = value.trim().split('/').next()?;
+        remaining.trim().parse().ok()
+    })
+}
+
 /// Check if YubiKey has TDES PIN-protected management key
 ///
 /// This is required for age-plugin-yubikey to work properly.
@@ -253,3 +285,18 @@ pub fn verify_yubikey_pin(serial: &str, pin: &str) -> Result<bool> {
     // The actual PIN verification happens during decryption operations
     Ok(true)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_pin_retries() {
+        let info = "PIV version:              5.4.3\n\
+                    PIN tries remaining:      2/3\n\
+                    PUK tries remaining:      3/3\n";
+        assert_eq!(parse_pin_retries(info), Some(2));
+        assert_eq!(parse_pin_retries("PIN tries remaining: 0/3"), Some(0));
+        assert_eq!(parse_pin_retries("PUK tries remaining: 3/3"), None);
+    }
+}
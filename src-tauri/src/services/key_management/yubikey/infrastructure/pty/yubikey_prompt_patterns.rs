@@ -37,6 +37,25 @@ pub fn is_error_output(line: &str) -> bool {
         || line.contains("Failed")
 }
 
+/// Check if output indicates the PIN was rejected or is blocked
+///
+/// Retrying after one of these burns a PIN attempt, so callers decrypting
+/// several archives must stop instead of moving on to the next one.
+pub fn is_pin_failure(output: &str) -> bool {
+    let output = output.to_lowercase();
+    [
+        "incorrect pin",
+        "wrong pin",
+        "invalid pin",
+        "pin verification failed",
+        "pin is blocked",
+        "pin blocked",
+        "tries remaining",
+    ]
+    .iter()
+    .any(|marker| output.contains(marker))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -73,4 +92,12 @@ fn test_error_detection() {
         assert!(is_error_output("Failed to connect"));
         assert!(!is_error_output("Successfully completed"));
     }
+
+    #[test]
+    fn test_pin_failure_detection() {
+        assert!(is_pin_failure("Error: Incorrect PIN (2 tries remaining)"));
+        assert!(is_pin_failure("age-plugin-yubikey: PIN is blocked"));
+        assert!(!is_pin_failure("Enter PIN for YubiKey with serial 123"));
+        assert!(!is_pin_failure("Age CLI process failed"));
+    }
 }
@@ -85,6 +85,8 @@ pub struct YubiKeyConfig {
     pub enable_events: bool,
     /// Enable operation logging
     pub enable_logging: bool,
+    /// Smart-card reader to bind operations to when several are attached
+    pub reader_hint: Option<String>,
 }
 
 impl Default for YubiKeyConfig {
@@ -94,6 +96,7 @@ fn default() -> Self {
             operation_timeout_secs: 30,
             enable_events: true,
             enable_logging: true,
+            reader_hint: None,
         }
     }
 }
@@ -133,6 +136,7 @@ fn test_config_defaults() {
         assert_eq!(config.operation_timeout_secs, 30);
         assert!(config.enable_events);
         assert!(config.enable_logging);
+        assert!(config.reader_hint.is_none());
     }
 
     #[test]
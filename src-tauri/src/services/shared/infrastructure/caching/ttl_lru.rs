@@ -2,32 +2,55 @@
 //!
 //! This module provides a thread-safe LRU cache with time-to-live (TTL) support
 //! for automatic expiration of cached entries.
+//!
+//! While the system clock is implausible, age can't be trusted, so entries
+//! expire after `DEGRADED_MAX_HITS` reads instead.
 
+use crate::services::shared::infrastructure::clock::{ClockService, ClockStamp};
 use lru::LruCache;
 use serde::{Deserialize, Serialize};
 use std::num::NonZeroUsize;
 use std::sync::{Arc, Mutex};
-use std::time::{Duration, SystemTime};
+use std::time::Duration;
+
+/// Reads an entry serves before expiring while the clock is unreliable
+pub const DEGRADED_MAX_HITS: u32 = 16;
 
 /// Cache entry with TTL support
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub(crate) struct CacheEntry<T> {
     pub data: T,
-    created_at: SystemTime,
+    created: ClockStamp,
     ttl_seconds: u64,
+    hits: u32,
 }
 
 impl<T> CacheEntry<T> {
+    #[cfg(test)]
     pub fn new(data: T, ttl_seconds: u64) -> Self {
+        Self::created_at(data, ttl_seconds, ClockService::global())
+    }
+
+    fn created_at(data: T, ttl_seconds: u64, clock: &ClockService) -> Self {
         Self {
             data,
-            created_at: SystemTime::now(),
+            created: clock.stamp(),
             ttl_seconds,
+            hits: 0,
         }
     }
 
+    #[cfg(test)]
     pub fn is_expired(&self) -> bool {
-        self.created_at.elapsed().unwrap_or(Duration::MAX) > Duration::from_secs(self.ttl_seconds)
+        self.is_expired_at(ClockService::global())
+    }
+
+    fn is_expired_at(&self, clock: &ClockService) -> bool {
+        if self.created.reliable && clock.is_reliable() {
+            clock.elapsed_since(&self.created) > Duration::from_secs(self.ttl_seconds)
+        } else {
+            self.hits >= DEGRADED_MAX_HITS
+        }
     }
 }
 
@@ -38,6 +61,7 @@ pub struct TtlLruCache<K, V>
 {
     pub(crate) cache: Arc<Mutex<LruCache<K, CacheEntry<V>>>>,
     pub(crate) default_ttl: u64,
+    clock: ClockService,
 }
 
 impl<K, V> std::fmt::Debug for TtlLruCache<K, V>
@@ -59,18 +83,24 @@ impl<K, V> TtlLruCache<K, V>
     V: Clone,
 {
     pub fn new(capacity: usize, default_ttl: u64) -> Self {
+        Self::with_clock(capacity, default_ttl, ClockService::global().clone())
+    }
+
+    pub fn with_clock(capacity: usize, default_ttl: u64, clock: ClockService) -> Self {
         Self {
             cache: Arc::new(Mutex::new(LruCache::new(
                 NonZeroUsize::new(capacity).unwrap(),
             ))),
             default_ttl,
+            clock,
         }
     }
 
     pub fn get(&self, key: &K) -> Option<V> {
         let mut cache = self.cache.lock().unwrap();
-        if let Some(entry) = cache.get(key) {
-            if !entry.is_expired() {
+        if let Some(entry) = cache.get_mut(key) {
+            if !entry.is_expired_at(&self.clock) {
+                entry.hits += 1;
                 return Some(entry.data.clone());
             } else {
                 // Remove expired entry
@@ -86,7 +116,7 @@ pub fn put(&self, key: K, value: V) {
 
     pub fn put_with_ttl(&self, key: K, value: V, ttl: u64) {
         let mut cache = self.cache.lock().unwrap();
-        let entry = CacheEntry::new(value, ttl);
+        let entry = CacheEntry::created_at(value, ttl, &self.clock);
         cache.put(key, entry);
     }
 
@@ -110,3 +140,54 @@ pub fn is_empty(&self) -> bool {
         cache.is_empty()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::shared::infrastructure::clock::testing::{FakeClock, service};
+
+    #[test]
+    fn test_expires_by_age_on_reliable_clock() {
+        let clock = FakeClock::at("2026-03-01T00:00:00Z");
+        let cache = TtlLruCache::with_clock(4, 60, service(&clock));
+
+        cache.put("key", 1);
+        clock.advance(Duration::from_secs(30));
+        assert_eq!(cache.get(&"key"), Some(1));
+        clock.advance(Duration::from_secs(31));
+        assert_eq!(cache.get(&"key"), None);
+    }
+
+    #[test]
+    fn test_expires_by_use_count_on_bad_clock() {
+        let clock = FakeClock::at("1970-01-01T00:00:00Z");
+        let cache = TtlLruCache::with_clock(4, 60, service(&clock));
+
+        cache.put("key", 1);
+        // Age is ignored: an hour on a 1970 clock means nothing
+        clock.advance(Duration::from_secs(3600));
+        for _ in 0..DEGRADED_MAX_HITS {
+            assert_eq!(cache.get(&"key"), Some(1));
+        }
+        assert_eq!(cache.get(&"key"), None);
+    }
+
+    #[test]
+    fn test_clock_jump_does_not_expire_or_pin_entries() {
+        let clock = FakeClock::at("2026-03-01T00:00:00Z");
+        let cache = TtlLruCache::with_clock(4, 60, service(&clock));
+        cache.put("key", 1);
+
+        // Clock jumps to 2040: the entry is not instantly 14 years old
+        clock.set_wall("2040-03-01T00:00:00Z");
+        assert_eq!(cache.get(&"key"), Some(1));
+
+        // Entries written on the bad clock still expire by use count
+        cache.put("late", 2);
+        clock.set_wall("2026-03-01T00:00:10Z");
+        for _ in 0..DEGRADED_MAX_HITS {
+            assert_eq!(cache.get(&"late"), Some(2));
+        }
+        assert_eq!(cache.get(&"late"), None);
+    }
+}
@@ -0,0 +1,465 @@
+//! Change notifications for the key registry and vaults
+//!
+//! Application services publish a `ChangeEvent` after each mutation so the
+//! UI can patch its lists instead of refetching them. Every event gets the
+//! next `registry_revision`; list commands return the revision they were
+//! built at, so a window that sees a gap in revisions knows it missed an
+//! event and falls back to a full refetch.
+//!
+//! The registry repository advances the same revision on every write or
+//! reload. The event published next takes that revision instead of another,
+//! so a write and its event count once; a write no event follows leaves a
+//! gap, and windows refetch. Writes and events take revisions under one
+//! lock, so a write can't land between the events of a batch.
+//!
+//! Events are only published once the write is durable. For journaled
+//! mutations use `apply_and_publish`, which publishes after the journal's
+//! completion record and skips mutations that changed nothing.
+//!
+//! Revisions count from zero at startup; windows refetch on launch anyway.
+
+use crate::error::StorageError;
+use crate::services::key_management::shared::KeyLifecycleStatus;
+use crate::services::key_management::yubikey::infrastructure::pty::app_handle::get_app_handle;
+use crate::services::shared::infrastructure::io::{MutationJournal, MutationPlan, PendingWrite};
+use serde::Serialize;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use tauri::Emitter;
+use tracing::{debug, warn};
+
+/// App-wide event carrying a `ChangeNotification`
+pub const REGISTRY_CHANGED_EVENT: &str = "registry-changed";
+
+/// A change to the key registry or a vault, with ids and changed fields only
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, specta::Type)]
+#[serde(tag = "type")]
+pub enum ChangeEvent {
+    VaultCreated {
+        vault_id: String,
+        name: String,
+    },
+    VaultDeleted {
+        vault_id: String,
+    },
+    ArchiveAdded {
+        vault_id: String,
+        archive_id: String,
+    },
+    KeyAdded {
+        key_id: String,
+        label: String,
+    },
+    KeyRenamed {
+        key_id: String,
+        label: String,
+    },
+    KeyAttached {
+        key_id: String,
+        vault_id: String,
+    },
+    KeyDetached {
+        key_id: String,
+        vault_id: String,
+    },
+    KeyReservationFulfilled {
+        key_id: String,
+        vault_id: String,
+    },
+    KeyDeactivated {
+        key_id: String,
+        status: KeyLifecycleStatus,
+    },
+    KeyRestored {
+        key_id: String,
+        status: KeyLifecycleStatus,
+    },
+    KeyRemoved {
+        key_id: String,
+    },
+    ContactAdded {
+        contact_id: String,
+        name: String,
+    },
+    ContactRemoved {
+        contact_id: String,
+    },
+}
+
+/// A published event and the registry revision it produced
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, specta::Type)]
+pub struct ChangeNotification {
+    pub revision: u64,
+    pub event: ChangeEvent,
+}
+
+type Listener = Arc<dyn Fn(&ChangeNotification) + Send + Sync>;
+
+/// Handle for removing a listener
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SubscriptionId(u64);
+
+#[derive(Debug, Default)]
+struct Counters {
+    /// Revision of the last registry write or published change
+    current: u64,
+    /// Revision of the last published change
+    announced: u64,
+}
+
+/// Registry revisions, advanced by registry writes and published changes
+#[derive(Debug, Default)]
+pub struct RegistryRevisions {
+    counters: Mutex<Counters>,
+}
+
+static REVISIONS: RegistryRevisions = RegistryRevisions::new();
+static NEXT_SUBSCRIPTION: AtomicU64 = AtomicU64::new(0);
+
+/// In-process listeners; publishing holds the lock so notifications are
+/// delivered in revision order
+static LISTENERS: Mutex<Vec<(SubscriptionId, Listener)>> = Mutex::new(Vec::new());
+
+impl RegistryRevisions {
+    pub const fn new() -> Self {
+        Self {
+            counters: Mutex::new(Counters {
+                current: 0,
+                announced: 0,
+            }),
+        }
+    }
+
+    /// Revisions of the running app, as list commands report them
+    pub fn global() -> &'static Self {
+        &REVISIONS
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Counters> {
+        self.counters.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    pub fn current(&self) -> u64 {
+        self.lock().current
+    }
+
+    /// Advance for a registry write or reload, returning the new revision
+    pub fn record_write(&self) -> u64 {
+        let mut counters = self.lock();
+        counters.current += 1;
+        counters.current
+    }
+
+    /// First of `count` consecutive revisions for a batch of events
+    ///
+    /// A registry write not yet announced lends its revision to the first
+    /// event.
+    fn announce(&self, count: u64) -> Option<u64> {
+        if count == 0 {
+            return None;
+        }
+        let mut counters = self.lock();
+        let first = if counters.current > counters.announced {
+            counters.current
+        } else {
+            counters.current + 1
+        };
+        counters.current = first + count - 1;
+        counters.announced = counters.current;
+        Some(first)
+    }
+}
+
+/// Revision of the last registry write or published change
+pub fn registry_revision() -> u64 {
+    REVISIONS.current()
+}
+
+/// Publish one change, returning its revision
+pub fn publish(event: ChangeEvent) -> u64 {
+    publish_all(vec![event])
+}
+
+/// Publish the changes of one operation under consecutive revisions,
+/// returning the last one
+pub fn publish_all(events: Vec<ChangeEvent>) -> u64 {
+    publish_with(&REVISIONS, events)
+}
+
+fn publish_with(revisions: &RegistryRevisions, events: Vec<ChangeEvent>) -> u64 {
+    let listeners = LISTENERS.lock().unwrap_or_else(|e| e.into_inner());
+    let app = get_app_handle();
+
+    let Some(first) = revisions.announce(events.len() as u64) else {
+        return revisions.current();
+    };
+    let mut last = first;
+    for (revision, event) in (first..).zip(events) {
+        last = revision;
+        let notification = ChangeNotification { revision, event };
+        debug!(revision = notification.revision, event = ?notification.event, "Publishing change");
+
+        if let Some(app) = &app
+            && let Err(e) = app.emit(REGISTRY_CHANGED_EVENT, &notification)
+        {
+            warn!(revision = notification.revision, error = %e, "Failed to emit change event");
+        }
+        for (_, listener) in listeners.iter() {
+            listener(&notification);
+        }
+    }
+    last
+}
+
+/// Apply a journaled mutation and publish its events once it has committed
+///
+/// Nothing is published if the mutation fails or leaves every file unchanged.
+pub fn apply_and_publish(
+    journal: &MutationJournal,
+    operation: &str,
+    writes: Vec<PendingWrite>,
+    events: Vec<ChangeEvent>,
+) -> Result<MutationPlan, StorageError> {
+    let plan = journal.apply(operation, writes)?;
+    if plan.changed_files().next().is_some() {
+        publish_all(events);
+    }
+    Ok(plan)
+}
+
+/// Call `listener` for every change published in this process
+pub fn subscribe(listener: impl Fn(&ChangeNotification) + Send + Sync + 'static) -> SubscriptionId {
+    let id = SubscriptionId(NEXT_SUBSCRIPTION.fetch_add(1, Ordering::SeqCst));
+    LISTENERS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .push((id, Arc::new(listener)));
+    id
+}
+
+pub fn unsubscribe(id: SubscriptionId) {
+    LISTENERS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .retain(|(existing, _)| *existing != id);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::shared::infrastructure::io::journal::FailAfterWrites;
+    use std::fs;
+    use std::path::{Path, PathBuf};
+    use tempfile::TempDir;
+
+    /// Registry and manifest files written together, as the services do
+    struct Store {
+        _temp: TempDir,
+        journal: MutationJournal,
+        registry: PathBuf,
+        manifest: PathBuf,
+    }
+
+    impl Store {
+        fn new() -> Self {
+            let temp = TempDir::new().unwrap();
+            let registry = temp.path().join("registry.json");
+            let manifest = temp.path().join("manifest.json");
+            fs::write(&registry, "{}").unwrap();
+            fs::write(&manifest, "{}").unwrap();
+            Self {
+                journal: MutationJournal::at(temp.path().join("journal")),
+                registry,
+                manifest,
+                _temp: temp,
+            }
+        }
+
+        fn writes(&self, registry: &str, manifest: &str) -> Vec<PendingWrite> {
+            vec![
+                PendingWrite::new(&self.registry, registry),
+                PendingWrite::new(&self.manifest, manifest),
+            ]
+        }
+    }
+
+    /// Record, for each event about `key_id`, what both files held when it
+    /// arrived
+    fn record_disk_state(
+        key_id: &str,
+        store: &Store,
+    ) -> (SubscriptionId, Arc<Mutex<Vec<(u64, String, String)>>>) {
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let (key_id, registry, manifest) = (
+            key_id.to_string(),
+            store.registry.clone(),
+            store.manifest.clone(),
+        );
+        let sink = Arc::clone(&seen);
+        let id = subscribe(move |notification| {
+            if format!("{:?}", notification.event).contains(&key_id) {
+                let read = |path: &Path| fs::read_to_string(path).unwrap();
+                sink.lock().unwrap().push((
+                    notification.revision,
+                    read(&registry),
+                    read(&manifest),
+                ));
+            }
+        });
+        (id, seen)
+    }
+
+    #[test]
+    fn test_events_follow_durable_writes() {
+        let cases = [
+            (
+                "attach",
+                "key-attach",
+                ChangeEvent::KeyAttached {
+                    key_id: "key-attach".to_string(),
+                    vault_id: "vault-1".to_string(),
+                },
+                r#"{"vaults":["vault-1"]}"#,
+                r#"{"recipients":["key-attach"]}"#,
+            ),
+            (
+                "remove",
+                "key-detach",
+                ChangeEvent::KeyDetached {
+                    key_id: "key-detach".to_string(),
+                    vault_id: "vault-1".to_string(),
+                },
+                r#"{"vaults":[]}"#,
+                r#"{"recipients":[]}"#,
+            ),
+            (
+                "rename",
+                "key-rename",
+                ChangeEvent::KeyRenamed {
+                    key_id: "key-rename".to_string(),
+                    label: "Laptop".to_string(),
+                },
+                r#"{"label":"Laptop"}"#,
+                r#"{"recipient_label":"Laptop"}"#,
+            ),
+        ];
+
+        for (name, key_id, event, registry, manifest) in cases {
+            let store = Store::new();
+            let (subscription, seen) = record_disk_state(key_id, &store);
+
+            apply_and_publish(
+                &store.journal,
+                name,
+                store.writes(registry, manifest),
+                vec![event],
+            )
+            .unwrap();
+            unsubscribe(subscription);
+
+            let seen = seen.lock().unwrap();
+            assert_eq!(seen.len(), 1, "case: {}", name);
+            assert_eq!(seen[0].1, registry, "case: {}", name);
+            assert_eq!(seen[0].2, manifest, "case: {}", name);
+        }
+    }
+
+    #[test]
+    fn test_failed_or_unchanged_mutation_publishes_nothing() {
+        let store = Store::new();
+        let (subscription, seen) = record_disk_state("key-failed", &store);
+        let event = || {
+            vec![ChangeEvent::KeyAttached {
+                key_id: "key-failed".to_string(),
+                vault_id: "vault-1".to_string(),
+            }]
+        };
+
+        // Crash after the registry write, before the manifest write
+        let failing = MutationJournal::at(store._temp.path().join("journal"))
+            .with_fail_point(FailAfterWrites(1));
+        let writes = store.writes(r#"{"vaults":["vault-1"]}"#, r#"{"recipients":["k"]}"#);
+        assert!(apply_and_publish(&failing, "attach", writes, event()).is_err());
+
+        // Writing the contents already on disk changes nothing
+        let store = Store::new();
+        apply_and_publish(&store.journal, "attach", store.writes("{}", "{}"), event()).unwrap();
+
+        unsubscribe(subscription);
+        assert!(seen.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_batch_gets_consecutive_increasing_revisions() {
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&seen);
+        let subscription = subscribe(move |notification| {
+            sink.lock().unwrap().push(notification.clone());
+        });
+
+        let revisions = RegistryRevisions::new();
+        let events: Vec<ChangeEvent> = (0..5)
+            .map(|i| ChangeEvent::KeyRenamed {
+                key_id: format!("batch-key-{i}"),
+                label: format!("Key {i}"),
+            })
+            .collect();
+        let last = publish_with(&revisions, events.clone());
+        unsubscribe(subscription);
+
+        let seen = seen.lock().unwrap();
+        let batch: Vec<u64> = seen
+            .iter()
+            .filter(|n| events.contains(&n.event))
+            .map(|n| n.revision)
+            .collect();
+        assert_eq!(batch, vec![1, 2, 3, 4, 5]);
+        assert_eq!(last, 5);
+        assert_eq!(revisions.current(), 5);
+        assert_eq!(publish_with(&revisions, vec![]), 5);
+    }
+
+    #[test]
+    fn test_registry_write_and_its_event_share_a_revision() {
+        let revisions = RegistryRevisions::new();
+        assert_eq!(revisions.record_write(), 1);
+        let event = || ChangeEvent::KeyAdded {
+            key_id: "written-key".to_string(),
+            label: "Written".to_string(),
+        };
+
+        // The write's revision goes to its event, once
+        assert_eq!(publish_with(&revisions, vec![event()]), 1);
+        assert_eq!(publish_with(&revisions, vec![event()]), 2);
+
+        // A write no event follows leaves a gap
+        assert_eq!(revisions.record_write(), 3);
+        assert_eq!(revisions.record_write(), 4);
+        assert_eq!(publish_with(&revisions, vec![event(), event()]), 5);
+        assert_eq!(revisions.current(), 5);
+    }
+
+    #[test]
+    fn test_concurrent_writes_land_outside_batches() {
+        let revisions = Arc::new(RegistryRevisions::new());
+        let writer = {
+            let revisions = Arc::clone(&revisions);
+            std::thread::spawn(move || {
+                (0..500)
+                    .map(|_| revisions.record_write())
+                    .collect::<Vec<u64>>()
+            })
+        };
+        let batches: Vec<u64> = (0..200).map(|_| revisions.announce(3).unwrap()).collect();
+        let writes = writer.join().unwrap();
+
+        // Only a batch's first revision can be a write's, lent to its event
+        for first in &batches {
+            assert!(!writes.contains(&(first + 1)));
+            assert!(!writes.contains(&(first + 2)));
+        }
+        for pair in batches.windows(2) {
+            assert!(pair[1] > pair[0] + 2);
+        }
+    }
+}
@@ -0,0 +1,375 @@
+//! Clock service
+//!
+//! Air-gapped restore machines often have a dead RTC battery, leaving the
+//! clock at 1970 or decades ahead. Code that makes decisions from the current
+//! time (cache expiry, backup freshness, staleness checks) asks this service
+//! rather than calling `Utc::now()` itself, so an implausible clock is noticed
+//! and those features degrade instead of misbehaving quietly:
+//!
+//! - TTL caches evict by use count instead of age
+//! - backup freshness is reported as unknown
+//! - staleness is measured on the monotonic clock
+//!
+//! Timestamps that are only recorded (manifests, history) still use the raw
+//! clock, flagged as unreliable where the format allows.
+
+use crate::logging::BUILD_TIMESTAMP;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, OnceLock};
+use std::time::{Duration, Instant};
+use tracing::warn;
+
+/// A clock this far past the build is treated as wrong
+pub const MAX_DAYS_AHEAD_OF_BUILD: i64 = 10 * 365;
+
+/// Earliest plausible time when the build timestamp can't be parsed
+const FALLBACK_EARLIEST: &str = "2025-01-01T00:00:00Z";
+
+/// Source of the current time
+pub trait Clock: Send + Sync {
+    fn now(&self) -> DateTime<Utc>;
+
+    /// Time since this process started; never goes backwards
+    fn monotonic(&self) -> Duration {
+        process_start().elapsed()
+    }
+}
+
+/// Wall-clock time
+#[derive(Debug, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+fn process_start() -> Instant {
+    static START: OnceLock<Instant> = OnceLock::new();
+    *START.get_or_init(Instant::now)
+}
+
+/// Whether the wall clock can be used for decisions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+#[serde(rename_all = "snake_case")]
+pub enum ClockReliability {
+    Reliable,
+    /// Earlier than this build was made
+    BeforeBuild,
+    /// Implausibly far past the build
+    FarFuture,
+}
+
+/// Range of wall-clock times considered plausible
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClockPolicy {
+    pub earliest: Option<DateTime<Utc>>,
+    pub latest: Option<DateTime<Utc>>,
+}
+
+impl ClockPolicy {
+    /// Between the build timestamp and `MAX_DAYS_AHEAD_OF_BUILD` after it
+    pub fn for_build() -> Self {
+        let earliest = build_timestamp().unwrap_or_else(|| {
+            FALLBACK_EARLIEST
+                .parse()
+                .expect("fallback timestamp is valid")
+        });
+        Self {
+            earliest: Some(earliest),
+            latest: Some(earliest + chrono::Duration::days(MAX_DAYS_AHEAD_OF_BUILD)),
+        }
+    }
+
+    /// Accept any time
+    pub fn trust_all() -> Self {
+        Self {
+            earliest: None,
+            latest: None,
+        }
+    }
+
+    pub fn check(&self, now: DateTime<Utc>) -> ClockReliability {
+        if self.earliest.is_some_and(|earliest| now < earliest) {
+            ClockReliability::BeforeBuild
+        } else if self.latest.is_some_and(|latest| now > latest) {
+            ClockReliability::FarFuture
+        } else {
+            ClockReliability::Reliable
+        }
+    }
+}
+
+fn build_timestamp() -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(BUILD_TIMESTAMP)
+        .ok()
+        .map(|at| at.with_timezone(&Utc))
+}
+
+/// A point in time on both the wall and the monotonic clock
+///
+/// Monotonic readings are only comparable within the process that took them,
+/// so stamps are meant for in-memory state (cache entries, held locks).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ClockStamp {
+    pub at: DateTime<Utc>,
+    pub monotonic: Duration,
+    /// Whether `at` was taken from a plausible clock
+    pub reliable: bool,
+}
+
+/// Clock state reported by diagnostics
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, specta::Type)]
+pub struct ClockStatus {
+    pub reliability: ClockReliability,
+    pub now: DateTime<Utc>,
+    pub earliest_plausible: Option<DateTime<Utc>>,
+    pub latest_plausible: Option<DateTime<Utc>>,
+    /// What is degraded while the clock is wrong; `None` when reliable
+    pub warning: Option<String>,
+}
+
+/// Current time together with a plausibility check
+#[derive(Clone)]
+pub struct ClockService {
+    clock: Arc<dyn Clock>,
+    policy: ClockPolicy,
+}
+
+impl std::fmt::Debug for ClockService {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ClockService")
+            .field("policy", &self.policy)
+            .finish()
+    }
+}
+
+impl ClockService {
+    pub fn new(clock: Arc<dyn Clock>, policy: ClockPolicy) -> Self {
+        Self { clock, policy }
+    }
+
+    /// The system clock checked against this build
+    pub fn system() -> Self {
+        Self::new(Arc::new(SystemClock), ClockPolicy::for_build())
+    }
+
+    /// Use `clock` as-is, without plausibility checks
+    pub fn trusted(clock: Arc<dyn Clock>) -> Self {
+        Self::new(clock, ClockPolicy::trust_all())
+    }
+
+    /// Process-wide system clock service
+    ///
+    /// Logs a warning the first time an implausible clock is seen.
+    pub fn global() -> &'static Self {
+        static GLOBAL: OnceLock<ClockService> = OnceLock::new();
+        GLOBAL.get_or_init(|| {
+            let service = Self::system();
+            if let Some(warning) = service.status().warning {
+                warn!(now = %service.now(), "{warning}");
+            }
+            service
+        })
+    }
+
+    pub fn now(&self) -> DateTime<Utc> {
+        self.clock.now()
+    }
+
+    pub fn monotonic(&self) -> Duration {
+        self.clock.monotonic()
+    }
+
+    pub fn reliability(&self) -> ClockReliability {
+        self.policy.check(self.now())
+    }
+
+    pub fn is_reliable(&self) -> bool {
+        self.reliability() == ClockReliability::Reliable
+    }
+
+    pub fn stamp(&self) -> ClockStamp {
+        let at = self.now();
+        ClockStamp {
+            at,
+            monotonic: self.monotonic(),
+            reliable: self.policy.check(at) == ClockReliability::Reliable,
+        }
+    }
+
+    /// Time since `stamp`, on the wall clock when both readings are plausible
+    /// and on the monotonic clock otherwise
+    pub fn elapsed_since(&self, stamp: &ClockStamp) -> Duration {
+        let now = self.stamp();
+        if now.reliable && stamp.reliable {
+            (now.at - stamp.at).to_std().unwrap_or(Duration::ZERO)
+        } else {
+            now.monotonic.saturating_sub(stamp.monotonic)
+        }
+    }
+
+    /// Whole days since `at`, or `None` while the clock is unreliable
+    pub fn days_since(&self, at: DateTime<Utc>) -> Option<i64> {
+        let now = self.now();
+        (self.policy.check(now) == ClockReliability::Reliable).then(|| (now - at).num_days())
+    }
+
+    pub fn status(&self) -> ClockStatus {
+        let now = self.now();
+        let reliability = self.policy.check(now);
+        let warning = match reliability {
+            ClockReliability::Reliable => None,
+            ClockReliability::BeforeBuild => Some(
+                "System clock is earlier than this app was built; backup freshness is unknown \
+                 and caches expire by use count"
+                    .to_string(),
+            ),
+            ClockReliability::FarFuture => Some(
+                "System clock is implausibly far in the future; backup freshness is unknown \
+                 and caches expire by use count"
+                    .to_string(),
+            ),
+        };
+
+        ClockStatus {
+            reliability,
+            now,
+            earliest_plausible: self.policy.earliest,
+            latest_plausible: self.policy.latest,
+            warning,
+        }
+    }
+}
+
+impl Clock for ClockService {
+    fn now(&self) -> DateTime<Utc> {
+        ClockService::now(self)
+    }
+
+    fn monotonic(&self) -> Duration {
+        ClockService::monotonic(self)
+    }
+}
+
+/// Clocks for tests: a settable wall time and monotonic reading
+#[cfg(test)]
+pub(crate) mod testing {
+    use super::*;
+    use std::sync::Mutex;
+
+    #[derive(Debug, Clone)]
+    pub(crate) struct FakeClock(Arc<Mutex<(DateTime<Utc>, Duration)>>);
+
+    impl FakeClock {
+        pub(crate) fn at(now: &str) -> Self {
+            Self(Arc::new(Mutex::new((now.parse().unwrap(), Duration::ZERO))))
+        }
+
+        /// Move both the wall and the monotonic clock forward
+        pub(crate) fn advance(&self, by: Duration) {
+            let mut state = self.0.lock().unwrap();
+            state.0 += chrono::Duration::from_std(by).unwrap();
+            state.1 += by;
+        }
+
+        /// Jump the wall clock only, as when the RTC is reset
+        pub(crate) fn set_wall(&self, now: &str) {
+            self.0.lock().unwrap().0 = now.parse().unwrap();
+        }
+    }
+
+    impl Clock for FakeClock {
+        fn now(&self) -> DateTime<Utc> {
+            self.0.lock().unwrap().0
+        }
+
+        fn monotonic(&self) -> Duration {
+            self.0.lock().unwrap().1
+        }
+    }
+
+    /// Plausible from 2025 through 2035
+    pub(crate) fn test_policy() -> ClockPolicy {
+        ClockPolicy {
+            earliest: Some("2025-01-01T00:00:00Z".parse().unwrap()),
+            latest: Some("2035-01-01T00:00:00Z".parse().unwrap()),
+        }
+    }
+
+    pub(crate) fn service(clock: &FakeClock) -> ClockService {
+        ClockService::new(Arc::new(clock.clone()), test_policy())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::testing::{FakeClock, service};
+    use super::*;
+
+    #[test]
+    fn test_detects_implausible_clocks() {
+        let clock = FakeClock::at("2026-03-01T00:00:00Z");
+        let service = service(&clock);
+        assert!(service.is_reliable());
+        assert!(service.status().warning.is_none());
+
+        clock.set_wall("1970-01-01T00:00:00Z");
+        assert_eq!(service.reliability(), ClockReliability::BeforeBuild);
+        assert!(service.status().warning.is_some());
+
+        clock.set_wall("2040-06-01T00:00:00Z");
+        assert_eq!(service.reliability(), ClockReliability::FarFuture);
+    }
+
+    #[test]
+    fn test_elapsed_falls_back_to_monotonic_time() {
+        let clock = FakeClock::at("2026-03-01T00:00:00Z");
+        let service = service(&clock);
+
+        let stamp = service.stamp();
+        clock.advance(Duration::from_secs(90));
+        assert_eq!(service.elapsed_since(&stamp), Duration::from_secs(90));
+
+        // A clock reset to 1970 would make the stamp look 56 years in the future
+        clock.set_wall("1970-01-01T00:00:00Z");
+        clock.advance(Duration::from_secs(30));
+        assert_eq!(service.elapsed_since(&stamp), Duration::from_secs(120));
+
+        // Stamps taken on a bad clock keep using monotonic time after it's fixed
+        let bad_stamp = service.stamp();
+        assert!(!bad_stamp.reliable);
+        clock.set_wall("2026-03-01T00:05:00Z");
+        clock.advance(Duration::from_secs(10));
+        assert_eq!(service.elapsed_since(&bad_stamp), Duration::from_secs(10));
+    }
+
+    #[test]
+    fn test_days_since_unknown_on_bad_clock() {
+        let clock = FakeClock::at("2026-03-11T00:00:00Z");
+        let service = service(&clock);
+        let at = "2026-03-01T00:00:00Z".parse().unwrap();
+        assert_eq!(service.days_since(at), Some(10));
+
+        clock.set_wall("2040-01-01T00:00:00Z");
+        assert_eq!(service.days_since(at), None);
+    }
+
+    #[test]
+    fn test_build_policy_brackets_build_time() {
+        let policy = ClockPolicy::for_build();
+        let earliest = policy.earliest.unwrap();
+        assert!(policy.latest.unwrap() > earliest);
+        assert_eq!(policy.check(earliest), ClockReliability::Reliable);
+        assert_eq!(
+            policy.check(earliest - chrono::Duration::days(1)),
+            ClockReliability::BeforeBuild
+        );
+        assert_eq!(
+            ClockPolicy::trust_all().check(DateTime::<Utc>::UNIX_EPOCH),
+            ClockReliability::Reliable
+        );
+    }
+}
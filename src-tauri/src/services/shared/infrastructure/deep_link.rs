@@ -0,0 +1,427 @@
+//! `barqly-vault://` deep links
+//!
+//! Finder's Services menu (and anything else that can open a URL) hands files
+//! to the app as `barqly-vault://encrypt?paths=<p1>&paths=<p2>&vault=<vault>`.
+//! Each `paths` value is one percent-encoded absolute path or `file://` URL;
+//! `vault` is an optional vault id or name.
+//!
+//! Links come from outside the app, so parsing is strict: anything that isn't
+//! a well-formed encrypt link naming existing local files is rejected. Accepted
+//! links wait in a `DeepLinkQueue` until the user confirms or dismisses them,
+//! one at a time, so a second link never replaces one being confirmed.
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use thiserror::Error;
+use url::Url;
+
+use crate::services::file::infrastructure::file_operations::contains_traversal_attempt;
+
+/// URL scheme registered for the app
+pub const DEEP_LINK_SCHEME: &str = "barqly-vault";
+
+/// Longest link accepted, before any decoding
+pub const MAX_DEEP_LINK_LEN: usize = 64 * 1024;
+
+/// Most files a single link may name
+pub const MAX_DEEP_LINK_PATHS: usize = 256;
+
+/// Longest decoded path accepted
+pub const MAX_DEEP_LINK_PATH_LEN: usize = 4096;
+
+/// Most links waiting behind the one being confirmed
+pub const MAX_PENDING_DEEP_LINKS: usize = 16;
+
+/// Why a deep link was refused
+#[derive(Debug, Clone, Error, PartialEq, Eq)]
+pub enum DeepLinkError {
+    #[error("Link is {len} bytes; at most {MAX_DEEP_LINK_LEN} are accepted")]
+    TooLong { len: usize },
+
+    #[error("Link is malformed: {0}")]
+    Malformed(String),
+
+    #[error("Unsupported link scheme '{0}'")]
+    UnsupportedScheme(String),
+
+    #[error("Unsupported link action '{0}'")]
+    UnsupportedAction(String),
+
+    #[error("Link names no files")]
+    NoPaths,
+
+    #[error("Link names {0} files; at most {MAX_DEEP_LINK_PATHS} are accepted")]
+    TooManyPaths(usize),
+
+    #[error("Rejected path '{path}': {reason}")]
+    InvalidPath { path: String, reason: String },
+
+    #[error("Too many links are waiting for confirmation")]
+    QueueFull,
+
+    #[error("No vault was chosen for the link")]
+    NoVaultChosen,
+
+    #[error("No deep link '{0}' is awaiting confirmation")]
+    NotPending(String),
+}
+
+/// A parsed `encrypt` link
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EncryptDeepLink {
+    pub paths: Vec<PathBuf>,
+    /// Vault id or name, as given in the link
+    pub vault: Option<String>,
+}
+
+/// Parse and validate a `barqly-vault://encrypt` link
+///
+/// Every path must be absolute, free of traversal segments and NUL bytes, and
+/// name an existing file or directory.
+pub fn parse_deep_link(link: &str) -> Result<EncryptDeepLink, DeepLinkError> {
+    if link.len() > MAX_DEEP_LINK_LEN {
+        return Err(DeepLinkError::TooLong { len: link.len() });
+    }
+    if link.contains('\0') {
+        return Err(DeepLinkError::Malformed("contains a NUL byte".to_string()));
+    }
+
+    let url = Url::parse(link).map_err(|e| DeepLinkError::Malformed(e.to_string()))?;
+    if url.scheme() != DEEP_LINK_SCHEME {
+        return Err(DeepLinkError::UnsupportedScheme(url.scheme().to_string()));
+    }
+
+    // `barqly-vault://encrypt?..` puts the action in the host;
+    // `barqly-vault:encrypt?..` puts it in the path
+    let action = url
+        .host_str()
+        .filter(|host| !host.is_empty())
+        .unwrap_or_else(|| url.path().trim_matches('/'));
+    if !action.eq_ignore_ascii_case("encrypt") {
+        return Err(DeepLinkError::UnsupportedAction(action.to_string()));
+    }
+
+    let mut raw_paths = Vec::new();
+    let mut vault = None;
+    for (key, value) in url.query_pairs() {
+        match key.as_ref() {
+            "paths" | "path" => {
+                if raw_paths.len() == MAX_DEEP_LINK_PATHS {
+                    return Err(DeepLinkError::TooManyPaths(MAX_DEEP_LINK_PATHS + 1));
+                }
+                raw_paths.push(value.into_owned());
+            }
+            "vault" => {
+                let value = value.trim();
+                if value.contains('\0') {
+                    return Err(DeepLinkError::Malformed(
+                        "vault contains a NUL byte".to_string(),
+                    ));
+                }
+                vault = (!value.is_empty()).then(|| value.to_string());
+            }
+            _ => {}
+        }
+    }
+
+    if raw_paths.is_empty() {
+        return Err(DeepLinkError::NoPaths);
+    }
+
+    let paths = raw_paths
+        .iter()
+        .map(|raw| validate_path(raw))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(EncryptDeepLink { paths, vault })
+}
+
+fn validate_path(raw: &str) -> Result<PathBuf, DeepLinkError> {
+    let reject = |reason: &str| DeepLinkError::InvalidPath {
+        path: raw.chars().take(200).collect(),
+        reason: reason.to_string(),
+    };
+
+    if raw.contains('\0') {
+        return Err(reject("contains a NUL byte"));
+    }
+    if raw.len() > MAX_DEEP_LINK_PATH_LEN {
+        return Err(reject("path is too long"));
+    }
+
+    let path = if raw.starts_with("file:") {
+        Url::parse(raw)
+            .ok()
+            .and_then(|url| url.to_file_path().ok())
+            .ok_or_else(|| reject("not a local file URL"))?
+    } else if raw.contains("://") {
+        return Err(reject("not a local file path"));
+    } else {
+        PathBuf::from(raw)
+    };
+
+    if !path.is_absolute() {
+        return Err(reject("path is not absolute"));
+    }
+    if contains_traversal_attempt(&path) {
+        return Err(reject("path contains traversal segments"));
+    }
+    if !is_file_or_directory(&path) {
+        return Err(reject("no such file or folder"));
+    }
+    Ok(path)
+}
+
+fn is_file_or_directory(path: &Path) -> bool {
+    std::fs::metadata(path).is_ok_and(|metadata| metadata.is_file() || metadata.is_dir())
+}
+
+/// A link accepted for encryption, awaiting the user's confirmation
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, specta::Type)]
+pub struct PendingDeepLink {
+    pub request_id: String,
+    pub paths: Vec<String>,
+    /// Vault named by the link, as given
+    pub requested_vault: Option<String>,
+    /// Vault the link resolved to; `None` means the user has to choose
+    pub vault_id: Option<String>,
+    pub received_at: DateTime<Utc>,
+}
+
+/// A confirmed link, ready to encrypt
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfirmedDeepLink {
+    pub request_id: String,
+    pub vault_id: String,
+    pub paths: Vec<String>,
+}
+
+/// Tells the UI a link is ready for confirmation
+pub trait DeepLinkNotifier {
+    fn present(&self, link: &PendingDeepLink);
+}
+
+#[derive(Debug, Default)]
+struct QueueState {
+    active: Option<PendingDeepLink>,
+    waiting: VecDeque<PendingDeepLink>,
+}
+
+/// Links awaiting confirmation, presented one at a time in arrival order
+#[derive(Debug, Default)]
+pub struct DeepLinkQueue {
+    state: Mutex<QueueState>,
+}
+
+impl DeepLinkQueue {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Queue shared by the app's link handler and commands
+    pub fn global() -> &'static Self {
+        static QUEUE: OnceLock<DeepLinkQueue> = OnceLock::new();
+        QUEUE.get_or_init(Self::new)
+    }
+
+    /// Parse `link` and queue it
+    ///
+    /// `resolve_vault` maps the link's vault id or name to a vault id. The
+    /// link is presented right away when nothing else is being confirmed.
+    pub fn submit(
+        &self,
+        link: &str,
+        resolve_vault: impl FnOnce(&str) -> Option<String>,
+        notifier: &dyn DeepLinkNotifier,
+    ) -> Result<PendingDeepLink, DeepLinkError> {
+        let parsed = parse_deep_link(link)?;
+        let pending = PendingDeepLink {
+            request_id: uuid::Uuid::new_v4().to_string(),
+            paths: parsed
+                .paths
+                .iter()
+                .map(|path| path.display().to_string())
+                .collect(),
+            vault_id: parsed.vault.as_deref().and_then(resolve_vault),
+            requested_vault: parsed.vault,
+            received_at: Utc::now(),
+        };
+
+        let mut state = self.lock();
+        if state.active.is_none() {
+            state.active = Some(pending.clone());
+            drop(state);
+            notifier.present(&pending);
+        } else {
+            if state.waiting.len() >= MAX_PENDING_DEEP_LINKS {
+                return Err(DeepLinkError::QueueFull);
+            }
+            state.waiting.push_back(pending.clone());
+        }
+        Ok(pending)
+    }
+
+    /// Link currently being confirmed
+    pub fn current(&self) -> Option<PendingDeepLink> {
+        self.lock().active.clone()
+    }
+
+    /// Number of links waiting behind the current one
+    pub fn waiting(&self) -> usize {
+        self.lock().waiting.len()
+    }
+
+    /// Accept the current link for encryption into `vault_id`, or the vault
+    /// it resolved to when `vault_id` is `None`
+    ///
+    /// The link stays current until `finish` so links arriving while it
+    /// encrypts keep waiting.
+    pub fn confirm(
+        &self,
+        request_id: &str,
+        vault_id: Option<String>,
+    ) -> Result<ConfirmedDeepLink, DeepLinkError> {
+        let state = self.lock();
+        let active = state
+            .active
+            .as_ref()
+            .filter(|active| active.request_id == request_id)
+            .ok_or_else(|| DeepLinkError::NotPending(request_id.to_string()))?;
+        let vault_id = vault_id
+            .or_else(|| active.vault_id.clone())
+            .ok_or(DeepLinkError::NoVaultChosen)?;
+        Ok(ConfirmedDeepLink {
+            request_id: active.request_id.clone(),
+            vault_id,
+            paths: active.paths.clone(),
+        })
+    }
+
+    /// Drop the current link and present the next one
+    pub fn finish(
+        &self,
+        request_id: &str,
+        notifier: &dyn DeepLinkNotifier,
+    ) -> Result<Option<PendingDeepLink>, DeepLinkError> {
+        let mut state = self.lock();
+        if state.active.as_ref().map(|a| a.request_id.as_str()) != Some(request_id) {
+            return Err(DeepLinkError::NotPending(request_id.to_string()));
+        }
+        state.active = state.waiting.pop_front();
+        let next = state.active.clone();
+        drop(state);
+
+        if let Some(next) = &next {
+            notifier.present(next);
+        }
+        Ok(next)
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, QueueState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn encode(path: &Path) -> String {
+        url::form_urlencoded::byte_serialize(path.to_string_lossy().as_bytes()).collect()
+    }
+
+    #[test]
+    fn test_parse_decodes_paths_and_vault() {
+        let dir = TempDir::new().unwrap();
+        let file = dir.path().join("tax return 2024.pdf");
+        std::fs::write(&file, b"pdf").unwrap();
+
+        let link = format!(
+            "barqly-vault://encrypt?paths={}&paths={}&vault=Family%20Docs",
+            encode(&file),
+            encode(dir.path())
+        );
+        let parsed = parse_deep_link(&link).unwrap();
+        assert_eq!(parsed.paths, [file, dir.path().to_path_buf()]);
+        assert_eq!(parsed.vault.as_deref(), Some("Family Docs"));
+    }
+
+    #[test]
+    fn test_parse_rejects_foreign_schemes_and_actions() {
+        assert!(matches!(
+            parse_deep_link("javascript:alert(1)//barqly-vault://encrypt?paths=/etc"),
+            Err(DeepLinkError::UnsupportedScheme(scheme)) if scheme == "javascript"
+        ));
+        assert!(matches!(
+            parse_deep_link("https://evil.example/encrypt?paths=/tmp"),
+            Err(DeepLinkError::UnsupportedScheme(_))
+        ));
+        assert!(matches!(
+            parse_deep_link("barqly-vault://decrypt?paths=/tmp"),
+            Err(DeepLinkError::UnsupportedAction(_))
+        ));
+        assert!(matches!(
+            parse_deep_link("not a url"),
+            Err(DeepLinkError::Malformed(_))
+        ));
+        assert!(matches!(
+            parse_deep_link("barqly-vault://encrypt?vault=x"),
+            Err(DeepLinkError::NoPaths)
+        ));
+    }
+
+    #[test]
+    fn test_parse_rejects_hostile_paths() {
+        let dir = TempDir::new().unwrap();
+        let root = encode(dir.path());
+
+        let hostile = [
+            format!("barqly-vault://encrypt?paths={root}%00.txt"),
+            format!("barqly-vault://encrypt?paths={root}%2F..%2F..%2Fetc%2Fpasswd"),
+            "barqly-vault://encrypt?paths=relative%2Ffile.txt".to_string(),
+            "barqly-vault://encrypt?paths=javascript%3Aalert(1)".to_string(),
+            "barqly-vault://encrypt?paths=https%3A%2F%2Fevil.example%2Fx".to_string(),
+            format!("barqly-vault://encrypt?paths={root}%2Fmissing.txt"),
+            format!("barqly-vault://encrypt?paths={}", "a".repeat(5000)),
+        ];
+        for link in &hostile {
+            assert!(
+                matches!(
+                    parse_deep_link(link),
+                    Err(DeepLinkError::InvalidPath { .. })
+                ),
+                "accepted {link}"
+            );
+        }
+
+        let raw_nul = format!("barqly-vault://encrypt?paths={root}\0");
+        assert!(matches!(
+            parse_deep_link(&raw_nul),
+            Err(DeepLinkError::Malformed(_))
+        ));
+    }
+
+    #[test]
+    fn test_parse_rejects_oversized_links() {
+        let dir = TempDir::new().unwrap();
+        let overlong = format!(
+            "barqly-vault://encrypt?paths={}",
+            "x".repeat(MAX_DEEP_LINK_LEN)
+        );
+        assert!(matches!(
+            parse_deep_link(&overlong),
+            Err(DeepLinkError::TooLong { .. })
+        ));
+
+        let one = format!("paths={}", encode(dir.path()));
+        let many = vec![one; MAX_DEEP_LINK_PATHS + 1].join("&");
+        assert!(matches!(
+            parse_deep_link(&format!("barqly-vault://encrypt?{many}")),
+            Err(DeepLinkError::TooManyPaths(_))
+        ));
+    }
+}
@@ -132,7 +132,7 @@ fn save(&self, path: &Path) -> Result<(), StorageError> {
 ///
 /// Attempts to read the hostname using platform-specific methods.
 /// Returns `None` if hostname cannot be determined.
-fn get_hostname() -> Option<String> {
+pub fn get_hostname() -> Option<String> {
     // Try to get hostname from environment first (works across platforms)
     if let Ok(hostname) = std::env::var("HOSTNAME")
         && !hostname.is_empty()
@@ -169,6 +169,16 @@ fn get_hostname() -> Option<String> {
     None
 }
 
+/// Get the name of the OS account running the app
+///
+/// Returns `None` if the environment doesn't say.
+pub fn get_username() -> Option<String> {
+    ["USER", "LOGNAME", "USERNAME"]
+        .into_iter()
+        .filter_map(|var| std::env::var(var).ok())
+        .find(|name| !name.is_empty())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
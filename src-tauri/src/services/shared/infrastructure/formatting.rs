@@ -0,0 +1,373 @@
+//! Display formatting for sizes, dates and durations
+//!
+//! One place decides how the app writes "2.3 GB", "3 Nov 2024", "3 days ago"
+//! and "1 min 30 s", so summaries, notifications, exports and reports agree.
+//! Every function is pure: the locale is passed in and "now" is passed in,
+//! so output depends on nothing but the arguments.
+//!
+//! Policies:
+//! - sizes use decimal units (1 kB = 1000 bytes) with one decimal place, so
+//!   they match what file managers on macOS and most storage vendors show
+//! - dates and times are shown in UTC; relative forms ("3 days ago") are used
+//!   up to [`RELATIVE_DAYS_LIMIT`] days, then the date itself
+//! - machine-readable values (CSV size columns, JSON fields) are never
+//!   formatted; only display columns and messages are
+
+use chrono::{DateTime, Datelike, Timelike, Utc};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// Oldest (or furthest ahead) a time is shown relatively, in days
+pub const RELATIVE_DAYS_LIMIT: i64 = 7;
+
+/// A locale the app's display text is available in
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize, specta::Type,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum Locale {
+    #[default]
+    En,
+    De,
+}
+
+impl Locale {
+    pub const ALL: [Locale; 2] = [Locale::En, Locale::De];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::En => "en",
+            Self::De => "de",
+        }
+    }
+
+    /// Best available locale for a tag like `de-AT` or `en_GB`; English
+    /// when the language isn't supported
+    pub fn from_tag(tag: &str) -> Self {
+        let language = tag.split(['-', '_']).next().unwrap_or_default();
+        Self::ALL
+            .into_iter()
+            .find(|locale| locale.as_str().eq_ignore_ascii_case(language))
+            .unwrap_or_default()
+    }
+
+    fn catalog(&self) -> &'static Catalog {
+        match self {
+            Self::En => &EN,
+            Self::De => &DE,
+        }
+    }
+}
+
+/// Singular and plural form of a counted unit; `{n}` is the count
+struct Plural {
+    one: &'static str,
+    other: &'static str,
+}
+
+impl Plural {
+    fn format(&self, n: i64) -> String {
+        let pattern = if n == 1 { self.one } else { self.other };
+        pattern.replace("{n}", &n.to_string())
+    }
+}
+
+/// Display strings of one locale
+struct Catalog {
+    decimal_separator: &'static str,
+    /// Bytes, then kB through PB
+    byte_units: [&'static str; 6],
+    months: [&'static str; 12],
+    /// `{day}`, `{month}` and `{year}` placeholders
+    date_pattern: &'static str,
+    just_now: &'static str,
+    /// `{}` is a counted unit, e.g. "3 days"
+    past: &'static str,
+    future: &'static str,
+    minutes: Plural,
+    hours: Plural,
+    days: Plural,
+    /// Abbreviations for durations
+    hour_abbr: &'static str,
+    minute_abbr: &'static str,
+    second_abbr: &'static str,
+}
+
+static EN: Catalog = Catalog {
+    decimal_separator: ".",
+    byte_units: ["B", "kB", "MB", "GB", "TB", "PB"],
+    months: [
+        "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+    ],
+    date_pattern: "{day} {month} {year}",
+    just_now: "just now",
+    past: "{} ago",
+    future: "in {}",
+    minutes: Plural {
+        one: "{n} minute",
+        other: "{n} minutes",
+    },
+    hours: Plural {
+        one: "{n} hour",
+        other: "{n} hours",
+    },
+    days: Plural {
+        one: "{n} day",
+        other: "{n} days",
+    },
+    hour_abbr: "h",
+    minute_abbr: "min",
+    second_abbr: "s",
+};
+
+static DE: Catalog = Catalog {
+    decimal_separator: ",",
+    byte_units: ["Byte", "kB", "MB", "GB", "TB", "PB"],
+    months: [
+        "Jan.", "Feb.", "März", "Apr.", "Mai", "Juni", "Juli", "Aug.", "Sept.", "Okt.", "Nov.",
+        "Dez.",
+    ],
+    date_pattern: "{day}. {month} {year}",
+    just_now: "gerade eben",
+    past: "vor {}",
+    future: "in {}",
+    // Dative, as both "vor" and "in" take it
+    minutes: Plural {
+        one: "{n} Minute",
+        other: "{n} Minuten",
+    },
+    hours: Plural {
+        one: "{n} Stunde",
+        other: "{n} Stunden",
+    },
+    days: Plural {
+        one: "{n} Tag",
+        other: "{n} Tagen",
+    },
+    hour_abbr: "Std.",
+    minute_abbr: "Min.",
+    second_abbr: "s",
+};
+
+/// A number with one decimal place and the locale's separator
+fn one_decimal(value: f64, catalog: &Catalog) -> String {
+    format!("{value:.1}").replace('.', catalog.decimal_separator)
+}
+
+/// Size in decimal units with one decimal place, e.g. `2.3 GB` / `2,3 GB`
+///
+/// Counts under 1 kB are shown exactly (`512 B`).
+pub fn format_bytes(bytes: u64, locale: Locale) -> String {
+    const BASE: f64 = 1000.0;
+    let catalog = locale.catalog();
+    if bytes < 1000 {
+        return format!("{bytes} {}", catalog.byte_units[0]);
+    }
+
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    // Move up while the value would round to 1000.0 or more in this unit
+    while unit < catalog.byte_units.len() - 1 && (size * 10.0).round() / 10.0 >= BASE {
+        size /= BASE;
+        unit += 1;
+    }
+    format!(
+        "{} {}",
+        one_decimal(size, catalog),
+        catalog.byte_units[unit]
+    )
+}
+
+/// Calendar date in UTC, e.g. `3 Nov 2024` / `3. Nov. 2024`
+pub fn format_date(at: DateTime<Utc>, locale: Locale) -> String {
+    let catalog = locale.catalog();
+    catalog
+        .date_pattern
+        .replace("{day}", &at.day().to_string())
+        .replace("{month}", catalog.months[at.month0() as usize])
+        .replace("{year}", &at.year().to_string())
+}
+
+/// Date and time in UTC, e.g. `3 Nov 2024, 14:05 UTC`
+pub fn format_date_time(at: DateTime<Utc>, locale: Locale) -> String {
+    format!(
+        "{}, {:02}:{:02} UTC",
+        format_date(at, locale),
+        at.hour(),
+        at.minute()
+    )
+}
+
+/// `at` relative to `now`, e.g. `3 days ago` / `in 2 hours`
+///
+/// Under a minute either way reads "just now"; beyond
+/// [`RELATIVE_DAYS_LIMIT`] days it's the date (see [`format_date`]).
+pub fn format_relative(at: DateTime<Utc>, now: DateTime<Utc>, locale: Locale) -> String {
+    let catalog = locale.catalog();
+    let delta = now - at;
+    let (minutes, hours, days) = (
+        delta.num_minutes().abs(),
+        delta.num_hours().abs(),
+        delta.num_days().abs(),
+    );
+
+    let amount = if minutes < 1 {
+        return catalog.just_now.to_string();
+    } else if hours < 1 {
+        catalog.minutes.format(minutes)
+    } else if days < 1 {
+        catalog.hours.format(hours)
+    } else if days <= RELATIVE_DAYS_LIMIT {
+        catalog.days.format(days)
+    } else {
+        return format_date(at, locale);
+    };
+
+    let pattern = if at <= now {
+        catalog.past
+    } else {
+        catalog.future
+    };
+    pattern.replace("{}", &amount)
+}
+
+/// Duration for ETAs and timings, in its two most significant units:
+/// `850 ms`, `1.5 s`, `1 min 30 s`, `2 h 5 min`
+pub fn format_duration(duration: Duration, locale: Locale) -> String {
+    let catalog = locale.catalog();
+    let millis = duration.as_millis();
+    if millis < 1000 {
+        return format!("{millis} ms");
+    }
+
+    let secs = duration.as_secs();
+    let (hours, minutes, seconds) = (secs / 3600, (secs % 3600) / 60, secs % 60);
+    match (hours, minutes) {
+        (0, 0) => format!(
+            "{} {}",
+            one_decimal(millis as f64 / 1000.0, catalog),
+            catalog.second_abbr
+        ),
+        (0, _) => format!(
+            "{minutes} {} {seconds} {}",
+            catalog.minute_abbr, catalog.second_abbr
+        ),
+        _ => format!(
+            "{hours} {} {minutes} {}",
+            catalog.hour_abbr, catalog.minute_abbr
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration as ChronoDuration, TimeZone};
+
+    fn at(y: i32, m: u32, d: u32, h: u32, min: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(y, m, d, h, min, 0).unwrap()
+    }
+
+    /// Expected output per locale, English first
+    fn assert_snapshots<T: Copy>(cases: &[(T, &str, &str)], format: impl Fn(T, Locale) -> String) {
+        for (value, en, de) in cases {
+            assert_eq!(format(*value, Locale::En), *en);
+            assert_eq!(format(*value, Locale::De), *de);
+        }
+    }
+
+    #[test]
+    fn test_locale_from_tag() {
+        assert_eq!(Locale::from_tag("de-AT"), Locale::De);
+        assert_eq!(Locale::from_tag("DE"), Locale::De);
+        assert_eq!(Locale::from_tag("en_GB"), Locale::En);
+        assert_eq!(Locale::from_tag("fr-FR"), Locale::En);
+        assert_eq!(Locale::from_tag(""), Locale::En);
+    }
+
+    #[test]
+    fn test_format_bytes_snapshots() {
+        assert_snapshots(
+            &[
+                (0, "0 B", "0 Byte"),
+                (999, "999 B", "999 Byte"),
+                (1000, "1.0 kB", "1,0 kB"),
+                (1536, "1.5 kB", "1,5 kB"),
+                (999_949, "999.9 kB", "999,9 kB"),
+                // Would round to "1000.0 kB"
+                (999_950, "1.0 MB", "1,0 MB"),
+                (2_300_000_000, "2.3 GB", "2,3 GB"),
+                (1 << 40, "1.1 TB", "1,1 TB"),
+                (u64::MAX, "18446.7 PB", "18446,7 PB"),
+            ],
+            format_bytes,
+        );
+    }
+
+    #[test]
+    fn test_format_date_snapshots() {
+        assert_snapshots(
+            &[
+                (at(2024, 11, 3, 23, 59), "3 Nov 2024", "3. Nov. 2024"),
+                (at(2025, 3, 31, 0, 0), "31 Mar 2025", "31. März 2025"),
+            ],
+            format_date,
+        );
+        assert_eq!(
+            format_date_time(at(2024, 11, 3, 9, 5), Locale::En),
+            "3 Nov 2024, 09:05 UTC"
+        );
+        assert_eq!(
+            format_date_time(at(2024, 11, 3, 9, 5), Locale::De),
+            "3. Nov. 2024, 09:05 UTC"
+        );
+    }
+
+    #[test]
+    fn test_format_relative_snapshots() {
+        let now = at(2024, 11, 10, 12, 0);
+        let ago = |delta: ChronoDuration| now - delta;
+        assert_snapshots(
+            &[
+                (ago(ChronoDuration::seconds(30)), "just now", "gerade eben"),
+                (
+                    ago(ChronoDuration::minutes(1)),
+                    "1 minute ago",
+                    "vor 1 Minute",
+                ),
+                (
+                    ago(ChronoDuration::minutes(59)),
+                    "59 minutes ago",
+                    "vor 59 Minuten",
+                ),
+                (
+                    ago(ChronoDuration::hours(5)),
+                    "5 hours ago",
+                    "vor 5 Stunden",
+                ),
+                (ago(ChronoDuration::days(1)), "1 day ago", "vor 1 Tag"),
+                (ago(ChronoDuration::days(3)), "3 days ago", "vor 3 Tagen"),
+                (ago(ChronoDuration::days(7)), "7 days ago", "vor 7 Tagen"),
+                // Past the limit: the date itself
+                (ago(ChronoDuration::days(8)), "2 Nov 2024", "2. Nov. 2024"),
+                (ago(ChronoDuration::hours(-2)), "in 2 hours", "in 2 Stunden"),
+                (ago(ChronoDuration::days(-3)), "in 3 days", "in 3 Tagen"),
+            ],
+            |at, locale| format_relative(at, now, locale),
+        );
+    }
+
+    #[test]
+    fn test_format_duration_snapshots() {
+        assert_snapshots(
+            &[
+                (Duration::ZERO, "0 ms", "0 ms"),
+                (Duration::from_millis(850), "850 ms", "850 ms"),
+                (Duration::from_millis(1500), "1.5 s", "1,5 s"),
+                (Duration::from_secs(90), "1 min 30 s", "1 Min. 30 s"),
+                (Duration::from_secs(3720), "1 h 2 min", "1 Std. 2 Min."),
+            ],
+            format_duration,
+        );
+    }
+}
@@ -2,10 +2,30 @@
 //!
 //! Provides safe, atomic file writing using the write-rename pattern to prevent
 //! data corruption if the process crashes mid-write.
+//!
+//! Where the filesystem can't replace a file atomically (see
+//! `fs_capabilities`), writes fall back to a safe swap instead: the new
+//! contents are written beside the target and verified, the current file is
+//! backed up, a swap record is written, and only then is the target replaced.
+//! `recover_interrupted_swap` finishes or undoes a swap cut short by a crash.
 
-use std::path::Path;
+use super::fs_capabilities::capabilities_for;
+use super::journal::RecoveryAction;
+use super::store_lock::StoreLock;
+use crate::logging::spawn_blocking;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
 use tokio::fs as async_fs;
 use tokio::io::AsyncWriteExt;
+use tracing::{debug, warn};
+
+/// Suffixes of the files kept beside the target during a safe swap
+const SWAP_NEW_SUFFIX: &str = "swap-new";
+const SWAP_OLD_SUFFIX: &str = "swap-old";
+const SWAP_RECORD_SUFFIX: &str = "swap";
 
 /// Atomically write data to a file using the write-rename pattern
 ///
@@ -16,7 +36,11 @@
 ///
 /// If the process crashes during steps 1-2, the original file remains untouched.
 /// The rename operation (step 3) is atomic on POSIX systems, ensuring the file
-/// is never partially written.
+/// is never partially written. Locations without atomic rename use a safe
+/// swap instead (see module docs).
+///
+/// Writes into a shared vault store hold the store lock, and fail with
+/// `StoreLockError::InUse` while another account holds it.
 ///
 /// # Arguments
 /// * `path` - The target file path
@@ -29,6 +53,17 @@ pub async fn atomic_write(
     path: &Path,
     data: &[u8],
 ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let _store_lock = StoreLock::for_write(path)?;
+    let capabilities = capabilities_for(parent_dir(path));
+    if capabilities.needs_safe_swap() {
+        let (path, data) = (path.to_path_buf(), data.to_vec());
+        spawn_blocking(move || {
+            safe_swap_write(&path, &data, capabilities.reliable_fsync, &StdSwapFs)
+        })
+        .await??;
+        return Ok(());
+    }
+
     // Create temp file path with .tmp extension
     let temp_path = path.with_extension("tmp");
 
@@ -55,8 +90,12 @@ pub fn atomic_write_sync(
     path: &Path,
     data: &[u8],
 ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-    use std::fs;
-    use std::io::Write;
+    let _store_lock = StoreLock::for_write(path)?;
+    let capabilities = capabilities_for(parent_dir(path));
+    if capabilities.needs_safe_swap() {
+        safe_swap_write(path, data, capabilities.reliable_fsync, &StdSwapFs)?;
+        return Ok(());
+    }
 
     // Create temp file path with .tmp extension
     let temp_path = path.with_extension("tmp");
@@ -77,6 +116,201 @@ pub fn atomic_write_sync(
     Ok(())
 }
 
+fn parent_dir(path: &Path) -> &Path {
+    path.parent().unwrap_or(Path::new("."))
+}
+
+/// Filesystem operations the safe swap can't take for granted
+pub(crate) trait SwapFs {
+    fn rename(&self, from: &Path, to: &Path) -> io::Result<()>;
+}
+
+struct StdSwapFs;
+
+impl SwapFs for StdSwapFs {
+    fn rename(&self, from: &Path, to: &Path) -> io::Result<()> {
+        fs::rename(from, to)
+    }
+}
+
+/// Written once the new contents and the backup are both in place
+#[derive(Debug, Serialize, Deserialize)]
+struct SwapRecord {
+    /// SHA-256 of the new contents
+    hash_after: String,
+    /// Whether the target existed and was backed up
+    backed_up: bool,
+}
+
+/// File kept beside `path` during a swap, e.g. `registry.json.swap-new`
+fn sidecar(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".");
+    name.push(suffix);
+    path.with_file_name(name)
+}
+
+/// Replace `path` without relying on atomic rename
+///
+/// The target is only touched after the new contents and a verified backup
+/// exist, and is read back afterwards. If it can't be renamed into place the
+/// new contents are copied over it instead.
+pub(crate) fn safe_swap_write(
+    path: &Path,
+    data: &[u8],
+    reliable_fsync: bool,
+    swap_fs: &dyn SwapFs,
+) -> io::Result<()> {
+    recover_interrupted_swap(path)?;
+
+    let new_path = sidecar(path, SWAP_NEW_SUFFIX);
+    let old_path = sidecar(path, SWAP_OLD_SUFFIX);
+    let record_path = sidecar(path, SWAP_RECORD_SUFFIX);
+
+    write_synced(&new_path, data, reliable_fsync)?;
+    verify_contents(&new_path, data)?;
+
+    let backed_up = path.exists();
+    if backed_up {
+        let original = fs::read(path)?;
+        write_synced(&old_path, &original, reliable_fsync)?;
+        verify_contents(&old_path, &original)?;
+    }
+
+    let record = SwapRecord {
+        hash_after: hash_bytes(data),
+        backed_up,
+    };
+    write_synced(&record_path, &serde_json::to_vec(&record)?, reliable_fsync)?;
+
+    let swapped = match swap_fs.rename(&new_path, path) {
+        Ok(()) => verify_contents(path, data),
+        Err(e) => {
+            debug!(path = %path.display(), error = %e, "Rename failed, copying over target");
+            write_synced(path, data, reliable_fsync).and_then(|()| verify_contents(path, data))
+        }
+    };
+
+    if let Err(e) = swapped {
+        warn!(path = %path.display(), error = %e, "Safe swap failed, restoring original");
+        restore_original(path, &old_path, backed_up)?;
+        remove_sidecars(path);
+        return Err(e);
+    }
+
+    remove_sidecars(path);
+    Ok(())
+}
+
+/// Finish or undo a safe swap of `path` interrupted by a crash
+///
+/// Returns `None` when there was nothing to resolve.
+pub fn recover_interrupted_swap(path: &Path) -> io::Result<Option<RecoveryAction>> {
+    let record_path = sidecar(path, SWAP_RECORD_SUFFIX);
+    let record: Option<SwapRecord> = fs::read(&record_path)
+        .ok()
+        .and_then(|bytes| serde_json::from_slice(&bytes).ok());
+
+    let Some(record) = record else {
+        // No usable record: the crash came before the target was touched
+        remove_sidecars(path);
+        return Ok(None);
+    };
+
+    let new_path = sidecar(path, SWAP_NEW_SUFFIX);
+    let old_path = sidecar(path, SWAP_OLD_SUFFIX);
+
+    let action = if hash_of(path) == Some(record.hash_after.clone()) {
+        RecoveryAction::Replayed
+    } else if hash_of(&new_path) == Some(record.hash_after.clone()) {
+        let contents = fs::read(&new_path)?;
+        write_synced(path, &contents, false)?;
+        verify_contents(path, &contents)?;
+        RecoveryAction::Replayed
+    } else {
+        restore_original(path, &old_path, record.backed_up)?;
+        RecoveryAction::RolledBack
+    };
+
+    warn!(path = %path.display(), action = ?action, "Recovered interrupted safe swap");
+    remove_sidecars(path);
+    Ok(Some(action))
+}
+
+/// Resolve every interrupted safe swap in `dir` (not recursive)
+pub fn recover_interrupted_swaps(dir: &Path) -> io::Result<Vec<(PathBuf, RecoveryAction)>> {
+    let record_suffix = format!(".{SWAP_RECORD_SUFFIX}");
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Ok(Vec::new());
+    };
+
+    let mut recovered = Vec::new();
+    for entry in entries.flatten() {
+        let name = entry.file_name().to_string_lossy().into_owned();
+        let Some(target) = name.strip_suffix(&record_suffix) else {
+            continue;
+        };
+        let target = dir.join(target);
+        if let Some(action) = recover_interrupted_swap(&target)? {
+            recovered.push((target, action));
+        }
+    }
+    Ok(recovered)
+}
+
+fn restore_original(path: &Path, old_path: &Path, backed_up: bool) -> io::Result<()> {
+    if !backed_up {
+        return match fs::remove_file(path) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        };
+    }
+
+    let original = fs::read(old_path)?;
+    write_synced(path, &original, false)?;
+    verify_contents(path, &original)
+}
+
+fn remove_sidecars(path: &Path) {
+    for suffix in [SWAP_RECORD_SUFFIX, SWAP_NEW_SUFFIX, SWAP_OLD_SUFFIX] {
+        let _ = fs::remove_file(sidecar(path, suffix));
+    }
+}
+
+/// Write and sync a file; sync errors only count where fsync is trusted,
+/// since the caller reads the file back either way
+fn write_synced(path: &Path, data: &[u8], reliable_fsync: bool) -> io::Result<()> {
+    let mut file = fs::File::create(path)?;
+    file.write_all(data)?;
+    match file.sync_all() {
+        Err(e) if reliable_fsync => Err(e),
+        Err(e) => {
+            debug!(path = %path.display(), error = %e, "Ignoring fsync error");
+            Ok(())
+        }
+        Ok(()) => Ok(()),
+    }
+}
+
+fn verify_contents(path: &Path, expected: &[u8]) -> io::Result<()> {
+    if fs::read(path)? == expected {
+        Ok(())
+    } else {
+        Err(io::Error::other(format!(
+            "{} doesn't match what was written",
+            path.display()
+        )))
+    }
+}
+
+fn hash_bytes(data: &[u8]) -> String {
+    hex::encode(Sha256::digest(data))
+}
+
+fn hash_of(path: &Path) -> Option<String> {
+    fs::read(path).ok().map(|contents| hash_bytes(&contents))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -137,4 +371,116 @@ fn test_atomic_write_sync_creates_file() {
         let content = std::fs::read(&file_path).unwrap();
         assert_eq!(content, data);
     }
+
+    /// Filesystem where renaming over a file never works, like some SMB shares
+    struct RenameFails;
+
+    impl SwapFs for RenameFails {
+        fn rename(&self, _from: &Path, _to: &Path) -> io::Result<()> {
+            Err(io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                "rename not supported",
+            ))
+        }
+    }
+
+    fn leftovers(dir: &Path) -> Vec<String> {
+        let mut names: Vec<String> = std::fs::read_dir(dir)
+            .unwrap()
+            .map(|e| e.unwrap().file_name().to_string_lossy().into_owned())
+            .filter(|name| name != "registry.json")
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn test_safe_swap_when_rename_fails() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("registry.json");
+
+        safe_swap_write(&file_path, b"first", true, &RenameFails).unwrap();
+        assert_eq!(std::fs::read(&file_path).unwrap(), b"first");
+
+        safe_swap_write(&file_path, b"second", false, &RenameFails).unwrap();
+        assert_eq!(std::fs::read(&file_path).unwrap(), b"second");
+        assert!(leftovers(temp_dir.path()).is_empty());
+    }
+
+    #[test]
+    fn test_safe_swap_with_working_rename() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("registry.json");
+        std::fs::write(&file_path, b"old").unwrap();
+
+        safe_swap_write(&file_path, b"new", true, &StdSwapFs).unwrap();
+        assert_eq!(std::fs::read(&file_path).unwrap(), b"new");
+        assert!(leftovers(temp_dir.path()).is_empty());
+    }
+
+    #[test]
+    fn test_recover_swap_torn_before_new_contents_landed() {
+        // Crash while copying over the target: record and backup exist,
+        // target is half written, staged copy is gone
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("registry.json");
+        std::fs::write(&file_path, b"new con").unwrap();
+        std::fs::write(sidecar(&file_path, SWAP_OLD_SUFFIX), b"original").unwrap();
+        let record = SwapRecord {
+            hash_after: hash_bytes(b"new contents"),
+            backed_up: true,
+        };
+        std::fs::write(
+            sidecar(&file_path, SWAP_RECORD_SUFFIX),
+            serde_json::to_vec(&record).unwrap(),
+        )
+        .unwrap();
+
+        let recovered = recover_interrupted_swaps(temp_dir.path()).unwrap();
+        assert_eq!(
+            recovered,
+            vec![(file_path.clone(), RecoveryAction::RolledBack)]
+        );
+        assert_eq!(std::fs::read(&file_path).unwrap(), b"original");
+        assert!(leftovers(temp_dir.path()).is_empty());
+    }
+
+    #[test]
+    fn test_recover_swap_replays_verified_new_contents() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("registry.json");
+        std::fs::write(&file_path, b"original").unwrap();
+        std::fs::write(sidecar(&file_path, SWAP_OLD_SUFFIX), b"original").unwrap();
+        std::fs::write(sidecar(&file_path, SWAP_NEW_SUFFIX), b"new contents").unwrap();
+        let record = SwapRecord {
+            hash_after: hash_bytes(b"new contents"),
+            backed_up: true,
+        };
+        std::fs::write(
+            sidecar(&file_path, SWAP_RECORD_SUFFIX),
+            serde_json::to_vec(&record).unwrap(),
+        )
+        .unwrap();
+
+        // The next write resolves it first, then applies on top
+        assert_eq!(
+            recover_interrupted_swap(&file_path).unwrap(),
+            Some(RecoveryAction::Replayed)
+        );
+        assert_eq!(std::fs::read(&file_path).unwrap(), b"new contents");
+        assert!(leftovers(temp_dir.path()).is_empty());
+    }
+
+    #[test]
+    fn test_recover_ignores_swap_without_record() {
+        // Crash before the record was written: target was never touched
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("registry.json");
+        std::fs::write(&file_path, b"original").unwrap();
+        std::fs::write(sidecar(&file_path, SWAP_NEW_SUFFIX), b"partial").unwrap();
+
+        assert_eq!(recover_interrupted_swap(&file_path).unwrap(), None);
+        assert_eq!(std::fs::read(&file_path).unwrap(), b"original");
+        assert!(leftovers(temp_dir.path()).is_empty());
+    }
 }
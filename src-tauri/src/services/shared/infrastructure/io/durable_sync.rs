@@ -0,0 +1,75 @@
+//! Flushing written files all the way to the device
+//!
+//! A write returns once the data is in the OS cache. `sync_file` and
+//! `sync_dir` force the file's contents and its directory entry out to the
+//! device, and `read_back_sha256` hashes what the file holds afterwards.
+//! Callers go through `DurableStorage` so tests can stand in a simulated
+//! write cache.
+
+use sha2::{Digest, Sha256};
+use std::fs::{self, OpenOptions};
+use std::io;
+use std::path::Path;
+
+/// Disk operations used to make writes durable
+pub trait DurableStorage: Send + Sync {
+    /// Flush a file's contents and metadata to the device
+    fn sync_file(&self, path: &Path) -> io::Result<()>;
+    /// Flush a directory's entries, so a renamed-in file survives power loss
+    fn sync_dir(&self, dir: &Path) -> io::Result<()>;
+    /// SHA-256 of the file's contents, hex encoded
+    fn read_back_sha256(&self, path: &Path) -> io::Result<String>;
+}
+
+/// The real filesystem
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SystemDurableStorage;
+
+impl DurableStorage for SystemDurableStorage {
+    fn sync_file(&self, path: &Path) -> io::Result<()> {
+        // Windows only flushes handles opened for writing
+        OpenOptions::new()
+            .read(true)
+            .write(cfg!(windows))
+            .open(path)?
+            .sync_all()
+    }
+
+    #[cfg(unix)]
+    fn sync_dir(&self, dir: &Path) -> io::Result<()> {
+        fs::File::open(dir)?.sync_all()
+    }
+
+    /// Directory entries are flushed with the file on Windows
+    #[cfg(not(unix))]
+    fn sync_dir(&self, _dir: &Path) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn read_back_sha256(&self, path: &Path) -> io::Result<String> {
+        let mut hasher = Sha256::new();
+        io::copy(&mut fs::File::open(path)?, &mut hasher)?;
+        Ok(hex::encode(hasher.finalize()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_system_storage_syncs_and_reads_back() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("archive.age");
+        fs::write(&path, b"age archive").unwrap();
+
+        let storage = SystemDurableStorage;
+        storage.sync_file(&path).unwrap();
+        storage.sync_dir(temp_dir.path()).unwrap();
+        assert_eq!(
+            storage.read_back_sha256(&path).unwrap(),
+            hex::encode(Sha256::digest(b"age archive"))
+        );
+    }
+}
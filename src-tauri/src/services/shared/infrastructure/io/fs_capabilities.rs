@@ -0,0 +1,469 @@
+//! Filesystem capability detection
+//!
+//! The write-rename pattern in `atomic_write` relies on the target filesystem
+//! replacing files atomically and reporting fsync failures. SMB and NFS
+//! shares, FAT and exFAT drives don't reliably do either. Each location is
+//! probed once per mount and the result cached, so writers can pick a safer
+//! strategy and settings can explain the weaker guarantees.
+//!
+//! Cloud-sync folders are detected separately, per directory, from the
+//! marker files their clients leave behind.
+
+use crate::constants::{LINUX_MAX_PATH, MACOS_MAX_PATH, WINDOWS_MAX_PATH};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+use std::path::PathBuf;
+use std::sync::{Mutex, MutexGuard, OnceLock};
+use tracing::{debug, warn};
+
+/// File name lengths tried by the probe, longest first
+const PROBE_NAME_LENGTHS: &[usize] = &[255, 143, 100];
+
+/// Filesystem family, as far as it matters for write guarantees
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, specta::Type)]
+pub enum FilesystemKind {
+    /// Journaling local filesystem (APFS, ext4, NTFS, ...)
+    Local,
+    /// FAT32 or exFAT, typical of USB drives and SD cards
+    Fat,
+    /// SMB, NFS or another network share
+    Network,
+    /// Couldn't be determined; the probe results decide
+    Unknown,
+}
+
+impl FilesystemKind {
+    /// Classify a mount type as reported by the OS (`ext4`, `cifs`, `msdos`, ...)
+    pub fn from_mount_type(fs_type: &str) -> Self {
+        match fs_type.to_ascii_lowercase().as_str() {
+            "vfat" | "msdos" | "exfat" | "fat" | "fat32" => Self::Fat,
+            "cifs" | "smb3" | "smbfs" | "nfs" | "nfs4" | "afpfs" | "webdav" | "davfs" | "9p"
+            | "fuse.sshfs" | "fuse.rclone" => Self::Network,
+            "apfs" | "hfs" | "ext2" | "ext3" | "ext4" | "xfs" | "btrfs" | "zfs" | "f2fs"
+            | "ntfs" | "ntfs3" | "tmpfs" | "overlay" => Self::Local,
+            _ => Self::Unknown,
+        }
+    }
+}
+
+/// Write guarantees of the filesystem a location lives on
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, specta::Type)]
+pub struct FsCapabilities {
+    pub kind: FilesystemKind,
+    /// Renaming over an existing file in the same directory replaces it atomically
+    pub atomic_rename: bool,
+    /// fsync succeeds and can be relied on to report write failures
+    pub reliable_fsync: bool,
+    pub case_sensitive: bool,
+    /// Longest file name the probe could create
+    pub max_file_name_length: Option<u32>,
+    /// Platform limit on a full path
+    pub max_path_length: u32,
+    /// False when the location couldn't be probed and these are assumptions
+    pub probed: bool,
+}
+
+impl FsCapabilities {
+    /// Capabilities assumed for a filesystem kind without probing
+    pub fn assumed(kind: FilesystemKind) -> Self {
+        let trusted = kind == FilesystemKind::Local;
+        Self {
+            kind,
+            atomic_rename: trusted,
+            reliable_fsync: trusted,
+            case_sensitive: !cfg!(any(target_os = "macos", target_os = "windows")),
+            max_file_name_length: None,
+            max_path_length: platform_max_path(),
+            probed: false,
+        }
+    }
+
+    /// Writes to this location need the degraded write strategy
+    pub fn needs_safe_swap(&self) -> bool {
+        !self.atomic_rename
+    }
+
+    /// Guarantees this location doesn't give, as user-facing warnings
+    pub fn warnings(&self) -> Vec<String> {
+        let mut warnings = Vec::new();
+        if !self.atomic_rename {
+            warnings.push(
+                "Files can't be replaced atomically here. Barqly Vault keeps a backup copy \
+                 during each save, but avoid unplugging the drive while saving"
+                    .to_string(),
+            );
+        }
+        if !self.reliable_fsync {
+            warnings.push(
+                "This location may not report write failures. Saved files are read back \
+                 to verify them"
+                    .to_string(),
+            );
+        }
+        if self.kind == FilesystemKind::Network {
+            warnings.push(
+                "This is a network share. Keep it connected while Barqly Vault is saving"
+                    .to_string(),
+            );
+        }
+        warnings
+    }
+}
+
+/// Cloud-sync client whose folder a location is inside
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, specta::Type)]
+pub enum CloudSyncProvider {
+    Dropbox,
+    GoogleDrive,
+    OneDrive,
+    ICloudDrive,
+    SynologyDrive,
+}
+
+impl CloudSyncProvider {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Dropbox => "Dropbox",
+            Self::GoogleDrive => "Google Drive",
+            Self::OneDrive => "OneDrive",
+            Self::ICloudDrive => "iCloud Drive",
+            Self::SynologyDrive => "Synology Drive",
+        }
+    }
+
+    /// Warning about edits from other devices racing local writes
+    pub fn warning(self) -> String {
+        format!(
+            "This folder is synced by {}. Changes made on another device at the same time \
+             can overwrite each other; avoid editing the same vault from two devices at once",
+            self.name()
+        )
+    }
+
+    /// Provider marked by a directory's own name or its contents
+    fn marked_by(dir: &Path) -> Option<Self> {
+        const MARKERS: &[(&str, CloudSyncProvider)] = &[
+            (".dropbox", CloudSyncProvider::Dropbox),
+            (".dropbox.cache", CloudSyncProvider::Dropbox),
+            (".tmp.drivedownload", CloudSyncProvider::GoogleDrive),
+            (".tmp.driveupload", CloudSyncProvider::GoogleDrive),
+            (".SynologyDrive", CloudSyncProvider::SynologyDrive),
+        ];
+
+        let name = dir.file_name()?.to_string_lossy();
+        if name == "com~apple~CloudDocs" || name == "Mobile Documents" {
+            return Some(Self::ICloudDrive);
+        }
+        if name == "OneDrive" || name.starts_with("OneDrive - ") {
+            return Some(Self::OneDrive);
+        }
+
+        MARKERS
+            .iter()
+            .find(|(marker, _)| dir.join(marker).exists())
+            .map(|(_, provider)| *provider)
+    }
+}
+
+/// Cloud-sync client managing `dir`, found by walking up to the root
+pub fn detect_cloud_sync(dir: &Path) -> Option<CloudSyncProvider> {
+    dir.ancestors().find_map(CloudSyncProvider::marked_by)
+}
+
+/// Capabilities of the filesystem holding `dir`, probed once per mount
+pub fn capabilities_for(dir: &Path) -> FsCapabilities {
+    let dir = if dir.as_os_str().is_empty() {
+        Path::new(".")
+    } else {
+        dir
+    };
+    let key = mount_key(dir);
+
+    if let Some(cached) = lock_cache().get(&key) {
+        return cached.clone();
+    }
+
+    let kind = detect_kind(dir);
+    let capabilities = match probe(dir, kind) {
+        Ok(capabilities) => capabilities,
+        Err(e) => {
+            debug!(dir = %dir.display(), error = %e, "Filesystem probe failed, assuming");
+            // Don't cache guesses; the directory may just not exist yet
+            return FsCapabilities::assumed(kind);
+        }
+    };
+
+    if capabilities.needs_safe_swap() || !capabilities.reliable_fsync {
+        warn!(
+            dir = %dir.display(),
+            kind = ?capabilities.kind,
+            atomic_rename = capabilities.atomic_rename,
+            reliable_fsync = capabilities.reliable_fsync,
+            "Location has degraded write guarantees"
+        );
+    }
+
+    lock_cache().insert(key, capabilities.clone());
+    capabilities
+}
+
+fn lock_cache() -> MutexGuard<'static, HashMap<String, FsCapabilities>> {
+    static CACHE: OnceLock<Mutex<HashMap<String, FsCapabilities>>> = OnceLock::new();
+    CACHE
+        .get_or_init(Mutex::default)
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+}
+
+#[cfg(unix)]
+pub(super) fn mount_key(dir: &Path) -> String {
+    use std::os::unix::fs::MetadataExt;
+    match fs::metadata(dir) {
+        Ok(metadata) => format!("dev:{}", metadata.dev()),
+        Err(_) => dir.display().to_string(),
+    }
+}
+
+#[cfg(not(unix))]
+pub(super) fn mount_key(dir: &Path) -> String {
+    // Drive letter or UNC share
+    dir.components()
+        .next()
+        .map(|root| root.as_os_str().to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+fn platform_max_path() -> u32 {
+    let max = if cfg!(target_os = "windows") {
+        WINDOWS_MAX_PATH
+    } else if cfg!(target_os = "macos") {
+        MACOS_MAX_PATH
+    } else {
+        LINUX_MAX_PATH
+    };
+    max as u32
+}
+
+/// Probe a writable directory with throwaway files
+///
+/// Renaming over an existing file, fsync, case folding and long names are
+/// tried directly; the mount type can only downgrade what the probe found,
+/// since FAT and network filesystems pass a rename test without being
+/// crash-safe.
+pub fn probe(dir: &Path, kind: FilesystemKind) -> io::Result<FsCapabilities> {
+    let probe_dir = dir.join(format!(
+        ".barqly-fs-probe-{}",
+        uuid::Uuid::new_v4().simple()
+    ));
+    fs::create_dir(&probe_dir)?;
+    let result = probe_in(&probe_dir, kind);
+    let _ = fs::remove_dir_all(&probe_dir);
+    result
+}
+
+fn probe_in(dir: &Path, kind: FilesystemKind) -> io::Result<FsCapabilities> {
+    let target = dir.join("target");
+    let replacement = dir.join("replacement");
+    fs::write(&target, b"old")?;
+
+    let mut file = fs::File::create(&replacement)?;
+    file.write_all(b"new")?;
+    let fsync_ok = file.sync_all().is_ok();
+    drop(file);
+
+    let rename_ok = fs::rename(&replacement, &target).is_ok()
+        && fs::read(&target).is_ok_and(|contents| contents == b"new")
+        && !replacement.exists();
+
+    let case_sensitive = !dir.join("TARGET").exists();
+
+    let max_file_name_length = PROBE_NAME_LENGTHS
+        .iter()
+        .find(|&&len| fs::write(dir.join("n".repeat(len)), b"").is_ok())
+        .map(|&len| len as u32);
+
+    let trusted_kind = !matches!(kind, FilesystemKind::Fat | FilesystemKind::Network);
+    Ok(FsCapabilities {
+        kind,
+        atomic_rename: rename_ok && trusted_kind,
+        reliable_fsync: fsync_ok && kind != FilesystemKind::Network,
+        case_sensitive,
+        max_file_name_length,
+        max_path_length: platform_max_path(),
+        probed: true,
+    })
+}
+
+/// Filesystem kind of the mount holding `dir`
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+fn detect_kind(dir: &Path) -> FilesystemKind {
+    mount_type(dir)
+        .map(|fs_type| FilesystemKind::from_mount_type(&fs_type))
+        .unwrap_or(FilesystemKind::Unknown)
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+fn detect_kind(dir: &Path) -> FilesystemKind {
+    // UNC paths are network shares; drive types need platform APIs
+    if dir.to_string_lossy().starts_with(r"\\") {
+        FilesystemKind::Network
+    } else {
+        FilesystemKind::Unknown
+    }
+}
+
+/// Mount type of the filesystem holding `dir` (`ext4`, `apfs`, ...)
+#[cfg(target_os = "linux")]
+pub fn mount_type(dir: &Path) -> Option<String> {
+    let dir = fs::canonicalize(dir).unwrap_or_else(|_| dir.to_path_buf());
+    let mounts = fs::read_to_string("/proc/self/mounts").unwrap_or_default();
+    mount_type_for(
+        &dir,
+        mounts.lines().filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let mount_point = fields.nth(1)?;
+            let fs_type = fields.next()?;
+            Some((
+                PathBuf::from(mount_point.replace("\\040", " ")),
+                fs_type.to_string(),
+            ))
+        }),
+    )
+}
+
+/// Mount type of the filesystem holding `dir`, from `mount` output such as
+/// `/dev/disk4s1 on /Volumes/KEYS (exfat, local, nodev, nosuid)`
+#[cfg(target_os = "macos")]
+pub fn mount_type(dir: &Path) -> Option<String> {
+    let dir = fs::canonicalize(dir).unwrap_or_else(|_| dir.to_path_buf());
+    let output = std::process::Command::new("mount").output().ok()?;
+    let output = String::from_utf8_lossy(&output.stdout);
+    mount_type_for(
+        &dir,
+        output.lines().filter_map(|line| {
+            let (_, rest) = line.split_once(" on ")?;
+            let (mount_point, options) = rest.rsplit_once(" (")?;
+            let fs_type = options.split(',').next()?.trim();
+            Some((PathBuf::from(mount_point), fs_type.to_string()))
+        }),
+    )
+}
+
+/// Mount types need platform APIs here
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+pub fn mount_type(_dir: &Path) -> Option<String> {
+    None
+}
+
+/// Type of the innermost mount containing `dir`
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+fn mount_type_for(dir: &Path, mounts: impl Iterator<Item = (PathBuf, String)>) -> Option<String> {
+    mounts
+        .filter(|(mount_point, _)| dir.starts_with(mount_point))
+        .max_by_key(|(mount_point, _)| mount_point.components().count())
+        .map(|(_, fs_type)| fs_type)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(any(target_os = "linux", target_os = "macos"))]
+    use std::path::PathBuf;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_probe_local_temp_dir() {
+        let dir = TempDir::new().unwrap();
+        let capabilities = probe(dir.path(), FilesystemKind::Local).unwrap();
+
+        assert!(capabilities.probed);
+        assert!(capabilities.atomic_rename);
+        assert!(capabilities.max_file_name_length.is_some());
+        assert!(capabilities.warnings().is_empty());
+        // Probe files are cleaned up
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn test_fat_and_network_never_trusted_for_rename() {
+        let dir = TempDir::new().unwrap();
+        let fat = probe(dir.path(), FilesystemKind::Fat).unwrap();
+        assert!(!fat.atomic_rename);
+        assert!(fat.needs_safe_swap());
+
+        let network = probe(dir.path(), FilesystemKind::Network).unwrap();
+        assert!(!network.reliable_fsync);
+        assert_eq!(network.warnings().len(), 3);
+    }
+
+    #[test]
+    fn test_mount_type_classification() {
+        assert_eq!(
+            FilesystemKind::from_mount_type("exfat"),
+            FilesystemKind::Fat
+        );
+        assert_eq!(
+            FilesystemKind::from_mount_type("msdos"),
+            FilesystemKind::Fat
+        );
+        assert_eq!(
+            FilesystemKind::from_mount_type("cifs"),
+            FilesystemKind::Network
+        );
+        assert_eq!(
+            FilesystemKind::from_mount_type("smbfs"),
+            FilesystemKind::Network
+        );
+        assert_eq!(
+            FilesystemKind::from_mount_type("apfs"),
+            FilesystemKind::Local
+        );
+        assert_eq!(
+            FilesystemKind::from_mount_type("fuseblk"),
+            FilesystemKind::Unknown
+        );
+    }
+
+    #[test]
+    fn test_detect_cloud_sync_markers() {
+        let root = TempDir::new().unwrap();
+        let dropbox = root.path().join("Dropbox");
+        let vaults = dropbox.join("Backups").join("Barqly-Vaults");
+        fs::create_dir_all(&vaults).unwrap();
+        assert_eq!(detect_cloud_sync(&vaults), None);
+
+        fs::create_dir(dropbox.join(".dropbox.cache")).unwrap();
+        assert_eq!(detect_cloud_sync(&vaults), Some(CloudSyncProvider::Dropbox));
+
+        let onedrive = root.path().join("OneDrive - Contoso").join("Vaults");
+        assert_eq!(
+            detect_cloud_sync(&onedrive),
+            Some(CloudSyncProvider::OneDrive)
+        );
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "macos"))]
+    #[test]
+    fn test_innermost_mount_wins() {
+        let mounts = vec![
+            (PathBuf::from("/"), "apfs".to_string()),
+            (PathBuf::from("/Volumes/KEYS"), "exfat".to_string()),
+        ];
+        assert_eq!(
+            mount_type_for(
+                Path::new("/Volumes/KEYS/vaults"),
+                mounts.clone().into_iter()
+            )
+            .as_deref(),
+            Some("exfat")
+        );
+        assert_eq!(
+            mount_type_for(Path::new("/Users/me"), mounts.into_iter()).as_deref(),
+            Some("apfs")
+        );
+    }
+}
@@ -0,0 +1,421 @@
+//! Background I/O priority for long-running operations
+//!
+//! Encrypting tens of gigabytes can saturate the disk and make the whole
+//! machine sluggish. In `IoPriority::Background` an operation:
+//! - lowers the OS I/O priority of its working threads (`IOPOL_THROTTLE` on
+//!   macOS, the idle ionice class on Linux; unchanged on Windows),
+//! - hashes and copies with a single worker,
+//! - pauses after each write burst, longer when bursts slow down, since that
+//!   means other apps are queueing I/O on the same disk.
+//!
+//! Each operation registers its priority under its operation ID so it can be
+//! switched while it runs. The pipeline picks the change up at its next chunk
+//! boundary through `IoPacer::checkpoint`.
+
+use crate::types::IoPriority;
+use std::cell::Cell;
+use std::collections::HashMap;
+use std::io::{self, Read, Write};
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, OnceLock};
+use std::time::{Duration, Instant};
+use tracing::debug;
+
+/// Bytes copied between checkpoints by `IoPacer::copy`
+const PACED_CHUNK_SIZE: usize = 256 * 1024;
+
+/// Shortest pause after a background write burst
+const MIN_BACKGROUND_PAUSE: Duration = Duration::from_millis(2);
+
+/// Longest pause after a background write burst
+const MAX_BACKGROUND_PAUSE: Duration = Duration::from_millis(250);
+
+/// Slowdown beyond which pauses stop growing
+const MAX_QUEUE_PRESSURE: f64 = 4.0;
+
+impl IoPriority {
+    /// Workers to use where `normal` would run at full speed
+    pub fn worker_count(self, normal: usize) -> usize {
+        match self {
+            Self::Normal => normal.max(1),
+            Self::Background => 1,
+        }
+    }
+
+    fn from_u8(value: u8) -> Self {
+        match value {
+            1 => Self::Background,
+            _ => Self::Normal,
+        }
+    }
+
+    fn as_u8(self) -> u8 {
+        match self {
+            Self::Normal => 0,
+            Self::Background => 1,
+        }
+    }
+}
+
+/// Priority of one running operation, shared by everything working on it
+#[derive(Debug, Clone, Default)]
+pub struct OperationPriority(Arc<AtomicU8>);
+
+impl OperationPriority {
+    pub fn new(priority: IoPriority) -> Self {
+        Self(Arc::new(AtomicU8::new(priority.as_u8())))
+    }
+
+    pub fn get(&self) -> IoPriority {
+        IoPriority::from_u8(self.0.load(Ordering::Relaxed))
+    }
+
+    pub fn set(&self, priority: IoPriority) {
+        self.0.store(priority.as_u8(), Ordering::Relaxed);
+    }
+}
+
+fn lock_registry() -> MutexGuard<'static, HashMap<String, OperationPriority>> {
+    static REGISTRY: OnceLock<Mutex<HashMap<String, OperationPriority>>> = OnceLock::new();
+    REGISTRY
+        .get_or_init(Mutex::default)
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+}
+
+/// Registration of an operation's priority, removed on drop
+#[derive(Debug)]
+#[must_use = "the priority can no longer be switched once the registration is dropped"]
+pub struct PriorityRegistration {
+    operation_id: String,
+    priority: OperationPriority,
+}
+
+impl PriorityRegistration {
+    /// Handle the pipeline reads the current priority from
+    pub fn priority(&self) -> OperationPriority {
+        self.priority.clone()
+    }
+}
+
+impl Drop for PriorityRegistration {
+    fn drop(&mut self) {
+        let mut registry = lock_registry();
+        if registry
+            .get(&self.operation_id)
+            .is_some_and(|current| Arc::ptr_eq(&current.0, &self.priority.0))
+        {
+            registry.remove(&self.operation_id);
+        }
+    }
+}
+
+/// Register a running operation so its priority can be switched
+pub fn register_operation_priority(
+    operation_id: &str,
+    initial: IoPriority,
+) -> PriorityRegistration {
+    let priority = OperationPriority::new(initial);
+    lock_registry().insert(operation_id.to_string(), priority.clone());
+    PriorityRegistration {
+        operation_id: operation_id.to_string(),
+        priority,
+    }
+}
+
+/// Switch a running operation's priority
+///
+/// Returns false if no operation with this ID is running.
+pub fn set_operation_priority(operation_id: &str, priority: IoPriority) -> bool {
+    match lock_registry().get(operation_id) {
+        Some(current) => {
+            current.set(priority);
+            true
+        }
+        None => false,
+    }
+}
+
+/// Current priority of a running operation
+pub fn operation_priority(operation_id: &str) -> Option<IoPriority> {
+    lock_registry()
+        .get(operation_id)
+        .map(OperationPriority::get)
+}
+
+/// Waits between write bursts; replaced in tests
+pub trait PacingHook: Send + Sync {
+    fn pause(&self, duration: Duration);
+}
+
+/// Pauses by sleeping the working thread
+#[derive(Debug, Default)]
+pub struct SleepPacing;
+
+impl PacingHook for SleepPacing {
+    fn pause(&self, duration: Duration) {
+        std::thread::sleep(duration);
+    }
+}
+
+thread_local! {
+    /// OS I/O priority last applied to this thread
+    static APPLIED_PRIORITY: Cell<IoPriority> = const { Cell::new(IoPriority::Normal) };
+}
+
+/// Paces an operation's I/O according to its current priority
+pub struct IoPacer {
+    priority: OperationPriority,
+    hook: Arc<dyn PacingHook>,
+    /// Fastest write speed seen, in nanoseconds per byte; slower bursts
+    /// indicate queue pressure from other I/O
+    baseline: Mutex<Option<f64>>,
+}
+
+impl std::fmt::Debug for IoPacer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("IoPacer")
+            .field("priority", &self.priority.get())
+            .finish_non_exhaustive()
+    }
+}
+
+impl IoPacer {
+    pub fn new(priority: OperationPriority) -> Self {
+        Self::with_hook(priority, Arc::new(SleepPacing))
+    }
+
+    pub fn with_hook(priority: OperationPriority, hook: Arc<dyn PacingHook>) -> Self {
+        Self {
+            priority,
+            hook,
+            baseline: Mutex::new(None),
+        }
+    }
+
+    pub fn priority(&self) -> IoPriority {
+        self.priority.get()
+    }
+
+    /// Workers to use where `normal` would run at full speed
+    pub fn worker_count(&self, normal: usize) -> usize {
+        self.priority().worker_count(normal)
+    }
+
+    /// Chunk boundary: apply any priority change and pause in background mode
+    ///
+    /// `burst_bytes` were written in `burst_elapsed`. Returns the pause taken.
+    pub fn checkpoint(&self, burst_bytes: u64, burst_elapsed: Duration) -> Option<Duration> {
+        let priority = self.priority();
+        apply_to_current_thread(priority);
+
+        let pressure = self.record_burst(burst_bytes, burst_elapsed);
+        if priority == IoPriority::Normal {
+            return None;
+        }
+
+        let pause = burst_elapsed
+            .mul_f64(pressure)
+            .clamp(MIN_BACKGROUND_PAUSE, MAX_BACKGROUND_PAUSE);
+        self.hook.pause(pause);
+        Some(pause)
+    }
+
+    /// Copy `reader` into `writer`, with a checkpoint after every chunk
+    pub fn copy(&self, reader: &mut impl Read, writer: &mut impl Write) -> io::Result<u64> {
+        let mut buffer = vec![0u8; PACED_CHUNK_SIZE];
+        let mut total = 0u64;
+        loop {
+            let started = Instant::now();
+            let n = match reader.read(&mut buffer) {
+                Ok(0) => return Ok(total),
+                Ok(n) => n,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            };
+            writer.write_all(&buffer[..n])?;
+            total += n as u64;
+            self.checkpoint(n as u64, started.elapsed());
+        }
+    }
+
+    /// Slowdown of this burst relative to the fastest seen (1.0 = no pressure)
+    fn record_burst(&self, bytes: u64, elapsed: Duration) -> f64 {
+        if bytes == 0 {
+            return 1.0;
+        }
+        let rate = elapsed.as_nanos() as f64 / bytes as f64;
+        let mut baseline = self.baseline.lock().unwrap_or_else(|e| e.into_inner());
+        let fastest = baseline.map_or(rate, |fastest| fastest.min(rate));
+        *baseline = Some(fastest);
+        if fastest <= 0.0 {
+            return 1.0;
+        }
+        (rate / fastest).clamp(1.0, MAX_QUEUE_PRESSURE)
+    }
+}
+
+/// Apply `priority` to the calling thread's OS I/O priority, once per change
+fn apply_to_current_thread(priority: IoPriority) {
+    if APPLIED_PRIORITY.with(Cell::get) == priority {
+        return;
+    }
+    if let Err(e) = platform::apply(priority) {
+        debug!(?priority, error = %e, "OS I/O priority not changed");
+    }
+    APPLIED_PRIORITY.with(|applied| applied.set(priority));
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use crate::types::IoPriority;
+    use std::io;
+
+    pub fn apply(priority: IoPriority) -> io::Result<()> {
+        let policy = match priority {
+            IoPriority::Normal => libc::IOPOL_DEFAULT,
+            IoPriority::Background => libc::IOPOL_THROTTLE,
+        };
+        let result = unsafe {
+            libc::setiopolicy_np(libc::IOPOL_TYPE_DISK, libc::IOPOL_SCOPE_THREAD, policy)
+        };
+        if result == 0 {
+            Ok(())
+        } else {
+            Err(io::Error::last_os_error())
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+    use crate::types::IoPriority;
+    use std::io;
+
+    /// From linux/ioprio.h
+    const IOPRIO_WHO_PROCESS: libc::c_int = 1;
+    const IOPRIO_CLASS_SHIFT: libc::c_int = 13;
+    const IOPRIO_CLASS_IDLE: libc::c_int = 3;
+
+    pub fn apply(priority: IoPriority) -> io::Result<()> {
+        // Class "none" follows the CPU nice level, the kernel default
+        let ioprio = match priority {
+            IoPriority::Normal => 0,
+            IoPriority::Background => IOPRIO_CLASS_IDLE << IOPRIO_CLASS_SHIFT,
+        };
+        // Who 0 is the calling thread
+        let result = unsafe { libc::syscall(libc::SYS_ioprio_set, IOPRIO_WHO_PROCESS, 0, ioprio) };
+        if result == 0 {
+            Ok(())
+        } else {
+            Err(io::Error::last_os_error())
+        }
+    }
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "linux")))]
+mod platform {
+    use crate::types::IoPriority;
+    use std::io;
+
+    pub fn apply(_priority: IoPriority) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Records pauses instead of sleeping
+    #[derive(Default)]
+    struct RecordingPacing(Mutex<Vec<Duration>>);
+
+    impl PacingHook for RecordingPacing {
+        fn pause(&self, duration: Duration) {
+            self.0.lock().unwrap().push(duration);
+        }
+    }
+
+    fn pacer(initial: IoPriority) -> (IoPacer, OperationPriority, Arc<RecordingPacing>) {
+        let priority = OperationPriority::new(initial);
+        let hook = Arc::new(RecordingPacing::default());
+        let pacer = IoPacer::with_hook(priority.clone(), hook.clone());
+        (pacer, priority, hook)
+    }
+
+    #[test]
+    fn test_normal_priority_never_pauses() {
+        let (pacer, _, hook) = pacer(IoPriority::Normal);
+        for _ in 0..5 {
+            assert_eq!(pacer.checkpoint(1024, Duration::from_millis(5)), None);
+        }
+        assert!(hook.0.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_background_pauses_grow_with_queue_pressure() {
+        let (pacer, _, hook) = pacer(IoPriority::Background);
+        let uncontended = pacer.checkpoint(1024, Duration::from_millis(10)).unwrap();
+        let contended = pacer.checkpoint(1024, Duration::from_millis(30)).unwrap();
+
+        assert_eq!(uncontended, Duration::from_millis(10));
+        assert_eq!(contended, Duration::from_millis(90));
+        assert_eq!(hook.0.lock().unwrap().len(), 2);
+        assert_eq!(
+            pacer.checkpoint(1024, Duration::from_secs(1)),
+            Some(MAX_BACKGROUND_PAUSE)
+        );
+    }
+
+    #[test]
+    fn test_priority_switches_at_next_checkpoint() {
+        let (pacer, priority, hook) = pacer(IoPriority::Normal);
+        assert_eq!(pacer.checkpoint(1024, Duration::from_millis(5)), None);
+
+        priority.set(IoPriority::Background);
+        assert!(pacer.checkpoint(1024, Duration::from_millis(5)).is_some());
+        assert_eq!(pacer.worker_count(4), 1);
+
+        priority.set(IoPriority::Normal);
+        assert_eq!(pacer.checkpoint(1024, Duration::from_millis(5)), None);
+        assert_eq!(pacer.worker_count(4), 4);
+        assert_eq!(hook.0.lock().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_copy_checkpoints_every_chunk() {
+        let (pacer, _, hook) = pacer(IoPriority::Background);
+        let data = vec![7u8; PACED_CHUNK_SIZE * 2 + 10];
+        let mut output = Vec::new();
+
+        let copied = pacer.copy(&mut data.as_slice(), &mut output).unwrap();
+
+        assert_eq!(copied, data.len() as u64);
+        assert_eq!(output, data);
+        assert_eq!(hook.0.lock().unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_registry_switches_running_operation() {
+        let registration = register_operation_priority("encrypt_test_1", IoPriority::Normal);
+        let handle = registration.priority();
+
+        assert!(set_operation_priority(
+            "encrypt_test_1",
+            IoPriority::Background
+        ));
+        assert_eq!(handle.get(), IoPriority::Background);
+        assert_eq!(
+            operation_priority("encrypt_test_1"),
+            Some(IoPriority::Background)
+        );
+
+        drop(registration);
+        assert!(!set_operation_priority(
+            "encrypt_test_1",
+            IoPriority::Normal
+        ));
+        assert_eq!(operation_priority("encrypt_test_1"), None);
+    }
+}
@@ -0,0 +1,722 @@
+//! Write-ahead journal for multi-file mutations
+//!
+//! Each file write is atomic on its own, but operations that update the key
+//! registry and a vault manifest together can still be torn by a crash between
+//! the two writes. The journal closes that gap:
+//!
+//! 1. Originals are backed up and the new contents are staged in the journal
+//!    directory
+//! 2. An intent record (operation, files, content hashes before/after) is appended
+//! 3. Each file is written atomically
+//! 4. A completion record is appended and the staged copies are removed
+//!
+//! On startup, `recover()` finds intents without a completion record and either
+//! replays them from the staged contents or, if staging is unusable, rolls the
+//! files back to their backed-up originals.
+//!
+//! `plan()` computes the same file list and hashes without touching anything,
+//! for dry runs.
+
+use crate::error::StorageError;
+use crate::services::shared::infrastructure::io::atomic_write_sync;
+use crate::services::shared::infrastructure::path_management::get_config_dir;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs::{self, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use tracing::{debug, error, info, warn};
+
+/// Journal log file name inside the journal directory
+const JOURNAL_FILE: &str = "mutations.jsonl";
+
+/// A file write to be performed as part of a journaled mutation
+#[derive(Debug, Clone)]
+pub struct PendingWrite {
+    pub path: PathBuf,
+    pub contents: Vec<u8>,
+}
+
+impl PendingWrite {
+    pub fn new(path: impl Into<PathBuf>, contents: impl Into<Vec<u8>>) -> Self {
+        Self {
+            path: path.into(),
+            contents: contents.into(),
+        }
+    }
+}
+
+/// Planned effect of a mutation on one file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlannedFileChange {
+    pub path: PathBuf,
+    /// SHA-256 of current contents, `None` if the file does not exist yet
+    pub hash_before: Option<String>,
+    pub hash_after: String,
+}
+
+impl PlannedFileChange {
+    pub fn is_noop(&self) -> bool {
+        self.hash_before.as_deref() == Some(self.hash_after.as_str())
+    }
+}
+
+/// Result of planning (dry run) or applying a mutation
+#[derive(Debug, Clone)]
+pub struct MutationPlan {
+    pub operation: String,
+    pub changes: Vec<PlannedFileChange>,
+}
+
+impl MutationPlan {
+    /// Files whose contents would actually change
+    pub fn changed_files(&self) -> impl Iterator<Item = &PlannedFileChange> {
+        self.changes.iter().filter(|c| !c.is_noop())
+    }
+}
+
+/// How an incomplete mutation was resolved during recovery
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecoveryAction {
+    /// Staged contents were written, completing the mutation
+    Replayed,
+    /// Files were restored to their contents before the mutation
+    RolledBack,
+}
+
+/// An incomplete mutation found during recovery
+#[derive(Debug, Clone)]
+pub struct RecoveredMutation {
+    pub id: String,
+    pub operation: String,
+    pub action: RecoveryAction,
+    pub files: Vec<PathBuf>,
+}
+
+/// Summary of journal recovery, reported by bootstrap
+#[derive(Debug, Clone, Default)]
+pub struct RecoveryReport {
+    pub recovered: Vec<RecoveredMutation>,
+}
+
+impl RecoveryReport {
+    pub fn is_empty(&self) -> bool {
+        self.recovered.is_empty()
+    }
+
+    pub fn count(&self, action: RecoveryAction) -> usize {
+        self.recovered.iter().filter(|m| m.action == action).count()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct JournalFileEntry {
+    path: PathBuf,
+    hash_before: Option<String>,
+    hash_after: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "record", rename_all = "snake_case")]
+enum JournalRecord {
+    Intent {
+        id: String,
+        operation: String,
+        created_at: DateTime<Utc>,
+        files: Vec<JournalFileEntry>,
+    },
+    Complete {
+        id: String,
+        completed_at: DateTime<Utc>,
+    },
+    RolledBack {
+        id: String,
+        rolled_back_at: DateTime<Utc>,
+    },
+}
+
+/// Injected crash point for tests: stop after this many file writes
+#[cfg(test)]
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct FailAfterWrites(pub usize);
+
+/// Write-ahead journal for registry and manifest mutations
+#[derive(Debug, Clone)]
+pub struct MutationJournal {
+    dir: PathBuf,
+    #[cfg(test)]
+    fail_after: Option<FailAfterWrites>,
+}
+
+impl MutationJournal {
+    /// Open the journal in the app config directory
+    pub fn open() -> Result<Self, StorageError> {
+        Ok(Self::at(get_config_dir()?.join("journal")))
+    }
+
+    /// Open a journal rooted at a specific directory
+    pub fn at(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            #[cfg(test)]
+            fail_after: None,
+        }
+    }
+
+    #[cfg(test)]
+    pub(crate) fn with_fail_point(mut self, fail_after: FailAfterWrites) -> Self {
+        self.fail_after = Some(fail_after);
+        self
+    }
+
+    #[cfg(test)]
+    fn hits_fail_point(&self, writes_done: usize) -> bool {
+        self.fail_after.is_some_and(|f| f.0 == writes_done)
+    }
+
+    #[cfg(not(test))]
+    fn hits_fail_point(&self, _writes_done: usize) -> bool {
+        false
+    }
+
+    /// Compute the effect of a mutation without writing anything (dry run)
+    pub fn plan(
+        &self,
+        operation: &str,
+        writes: &[PendingWrite],
+    ) -> Result<MutationPlan, StorageError> {
+        let changes = writes
+            .iter()
+            .map(|w| {
+                Ok(PlannedFileChange {
+                    path: w.path.clone(),
+                    hash_before: hash_file(&w.path)?,
+                    hash_after: hash_bytes(&w.contents),
+                })
+            })
+            .collect::<Result<Vec<_>, StorageError>>()?;
+
+        Ok(MutationPlan {
+            operation: operation.to_string(),
+            changes,
+        })
+    }
+
+    /// Apply all writes as one journaled mutation
+    pub fn apply(
+        &self,
+        operation: &str,
+        writes: Vec<PendingWrite>,
+    ) -> Result<MutationPlan, StorageError> {
+        let plan = self.plan(operation, &writes)?;
+        let (writes, files): (Vec<PendingWrite>, Vec<JournalFileEntry>) = writes
+            .into_iter()
+            .zip(&plan.changes)
+            .filter(|(_, change)| !change.is_noop())
+            .map(|(write, change)| {
+                let entry = JournalFileEntry {
+                    path: change.path.clone(),
+                    hash_before: change.hash_before.clone(),
+                    hash_after: change.hash_after.clone(),
+                };
+                (write, entry)
+            })
+            .unzip();
+
+        if writes.is_empty() {
+            debug!(operation, "Journaled mutation has no changes");
+            return Ok(plan);
+        }
+
+        let id = uuid::Uuid::new_v4().to_string();
+        let staging = self.staging_dir(&id);
+        create_private_dir(&staging)?;
+
+        for (index, write) in writes.iter().enumerate() {
+            if write.path.exists() {
+                copy_private(&write.path, &staging.join(backup_name(index)))?;
+            }
+            write_private(&staging.join(staged_name(index)), &write.contents)?;
+        }
+
+        self.append(&JournalRecord::Intent {
+            id: id.clone(),
+            operation: operation.to_string(),
+            created_at: Utc::now(),
+            files: files.clone(),
+        })?;
+
+        for (index, write) in writes.iter().enumerate() {
+            if self.hits_fail_point(index) {
+                return Err(StorageError::InitializationFailed(
+                    "Injected failure point".to_string(),
+                ));
+            }
+
+            if let Err(e) = write_preserving_permissions(&write.path, &write.contents) {
+                error!(operation, path = %write.path.display(), error = %e, "Journaled write failed, rolling back");
+                self.roll_back(&id, &files)?;
+                return Err(e);
+            }
+        }
+
+        self.append(&JournalRecord::Complete {
+            id: id.clone(),
+            completed_at: Utc::now(),
+        })?;
+        remove_staging(&staging);
+
+        debug!(operation, id = %id, files = files.len(), "Journaled mutation completed");
+        Ok(plan)
+    }
+
+    /// Resolve incomplete mutations left behind by a crash
+    pub fn recover(&self) -> Result<RecoveryReport, StorageError> {
+        let mut report = RecoveryReport::default();
+
+        for (id, operation, files) in self.incomplete_intents()? {
+            let action = if self.staging_is_usable(&id, &files) {
+                self.replay(&id, &files)?;
+                RecoveryAction::Replayed
+            } else {
+                self.roll_back(&id, &files)?;
+                RecoveryAction::RolledBack
+            };
+
+            warn!(
+                id = %id,
+                operation = %operation,
+                action = ?action,
+                "Recovered incomplete journaled mutation"
+            );
+
+            report.recovered.push(RecoveredMutation {
+                id,
+                operation,
+                action,
+                files: files.into_iter().map(|f| f.path).collect(),
+            });
+        }
+
+        self.compact()?;
+
+        if !report.is_empty() {
+            info!(
+                replayed = report.count(RecoveryAction::Replayed),
+                rolled_back = report.count(RecoveryAction::RolledBack),
+                "Journal recovery completed"
+            );
+        }
+
+        Ok(report)
+    }
+
+    fn replay(&self, id: &str, files: &[JournalFileEntry]) -> Result<(), StorageError> {
+        let staging = self.staging_dir(id);
+
+        for (index, file) in files.iter().enumerate() {
+            if hash_file(&file.path)?.as_deref() == Some(file.hash_after.as_str()) {
+                continue;
+            }
+            let contents = read(&staging.join(staged_name(index)))?;
+            write_preserving_permissions(&file.path, &contents)?;
+        }
+
+        self.append(&JournalRecord::Complete {
+            id: id.to_string(),
+            completed_at: Utc::now(),
+        })?;
+        remove_staging(&staging);
+        Ok(())
+    }
+
+    fn roll_back(&self, id: &str, files: &[JournalFileEntry]) -> Result<(), StorageError> {
+        let staging = self.staging_dir(id);
+
+        for (index, file) in files.iter().enumerate() {
+            let current = hash_file(&file.path)?;
+            if current == file.hash_before {
+                continue;
+            }
+
+            match &file.hash_before {
+                Some(_) => {
+                    let original = read(&staging.join(backup_name(index)))?;
+                    write_preserving_permissions(&file.path, &original)?;
+                }
+                None => {
+                    fs::remove_file(&file.path).map_err(|e| StorageError::FileWriteFailed {
+                        path: file.path.clone(),
+                        source: e,
+                    })?;
+                }
+            }
+        }
+
+        self.append(&JournalRecord::RolledBack {
+            id: id.to_string(),
+            rolled_back_at: Utc::now(),
+        })?;
+        remove_staging(&staging);
+        Ok(())
+    }
+
+    /// Staged contents exist and match the hashes recorded in the intent
+    fn staging_is_usable(&self, id: &str, files: &[JournalFileEntry]) -> bool {
+        let staging = self.staging_dir(id);
+        files.iter().enumerate().all(|(index, file)| {
+            fs::read(staging.join(staged_name(index)))
+                .map(|contents| hash_bytes(&contents) == file.hash_after)
+                .unwrap_or(false)
+        })
+    }
+
+    fn incomplete_intents(
+        &self,
+    ) -> Result<Vec<(String, String, Vec<JournalFileEntry>)>, StorageError> {
+        let path = self.journal_path();
+        if !path.exists() {
+            return Ok(Vec::new());
+        }
+
+        let file = fs::File::open(&path).map_err(|e| StorageError::FileReadFailed {
+            path: path.clone(),
+            source: e,
+        })?;
+
+        let mut pending: Vec<(String, String, Vec<JournalFileEntry>)> = Vec::new();
+        for line in BufReader::new(file).lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            // A torn final line means the crash happened while writing the intent;
+            // no file writes had started, so it is safe to ignore
+            let record: JournalRecord = match serde_json::from_str(&line) {
+                Ok(record) => record,
+                Err(e) => {
+                    warn!(error = %e, "Skipping unreadable journal record");
+                    continue;
+                }
+            };
+
+            match record {
+                JournalRecord::Intent {
+                    id,
+                    operation,
+                    files,
+                    ..
+                } => pending.push((id, operation, files)),
+                JournalRecord::Complete { id, .. } | JournalRecord::RolledBack { id, .. } => {
+                    pending.retain(|(pending_id, _, _)| *pending_id != id);
+                }
+            }
+        }
+
+        Ok(pending)
+    }
+
+    /// Truncate the log and remove orphaned staging once everything is resolved
+    fn compact(&self) -> Result<(), StorageError> {
+        let path = self.journal_path();
+        if path.exists() {
+            fs::remove_file(&path).map_err(|e| StorageError::FileWriteFailed {
+                path: path.clone(),
+                source: e,
+            })?;
+        }
+
+        if let Ok(entries) = fs::read_dir(&self.dir) {
+            for entry in entries.filter_map(|e| e.ok()) {
+                if entry.path().is_dir() {
+                    remove_staging(&entry.path());
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn append(&self, record: &JournalRecord) -> Result<(), StorageError> {
+        create_private_dir(&self.dir)?;
+        let path = self.journal_path();
+
+        let mut line = serde_json::to_string(record)?;
+        line.push('\n');
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .map_err(|e| StorageError::FileWriteFailed {
+                path: path.clone(),
+                source: e,
+            })?;
+        file.write_all(line.as_bytes())?;
+        file.sync_all()?;
+        Ok(())
+    }
+
+    fn journal_path(&self) -> PathBuf {
+        self.dir.join(JOURNAL_FILE)
+    }
+
+    fn staging_dir(&self, id: &str) -> PathBuf {
+        self.dir.join(id)
+    }
+}
+
+fn backup_name(index: usize) -> String {
+    format!("{index}.before")
+}
+
+fn staged_name(index: usize) -> String {
+    format!("{index}.after")
+}
+
+fn hash_bytes(data: &[u8]) -> String {
+    hex::encode(Sha256::digest(data))
+}
+
+fn hash_file(path: &Path) -> Result<Option<String>, StorageError> {
+    match fs::read(path) {
+        Ok(contents) => Ok(Some(hash_bytes(&contents))),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(StorageError::FileReadFailed {
+            path: path.to_path_buf(),
+            source: e,
+        }),
+    }
+}
+
+fn read(path: &Path) -> Result<Vec<u8>, StorageError> {
+    fs::read(path).map_err(|e| StorageError::FileReadFailed {
+        path: path.to_path_buf(),
+        source: e,
+    })
+}
+
+fn create_private_dir(dir: &Path) -> Result<(), StorageError> {
+    fs::create_dir_all(dir)
+        .map_err(|_| StorageError::DirectoryCreationFailed(dir.to_path_buf()))?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(dir, fs::Permissions::from_mode(0o700))?;
+    }
+
+    Ok(())
+}
+
+fn write_private(path: &Path, contents: &[u8]) -> Result<(), StorageError> {
+    atomic_write_sync(path, contents).map_err(|e| StorageError::FileWriteFailed {
+        path: path.to_path_buf(),
+        source: std::io::Error::other(e),
+    })?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(path, fs::Permissions::from_mode(0o600))?;
+    }
+
+    Ok(())
+}
+
+fn copy_private(from: &Path, to: &Path) -> Result<(), StorageError> {
+    write_private(to, &read(from)?)
+}
+
+/// Atomic write that keeps the target's existing permissions (e.g. 0600 registry)
+fn write_preserving_permissions(path: &Path, contents: &[u8]) -> Result<(), StorageError> {
+    let permissions = fs::metadata(path).ok().map(|m| m.permissions());
+
+    atomic_write_sync(path, contents).map_err(|e| StorageError::FileWriteFailed {
+        path: path.to_path_buf(),
+        source: std::io::Error::other(e),
+    })?;
+
+    if let Some(permissions) = permissions {
+        fs::set_permissions(path, permissions)?;
+    }
+
+    Ok(())
+}
+
+fn remove_staging(dir: &Path) {
+    match fs::remove_dir_all(dir) {
+        Ok(()) => {}
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+        Err(e) => warn!(path = %dir.display(), error = %e, "Failed to remove journal staging"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    struct Fixture {
+        _temp: TempDir,
+        journal_dir: PathBuf,
+        registry: PathBuf,
+        manifest: PathBuf,
+    }
+
+    /// Registry + manifest pair mirroring attach/detach
+    fn fixture() -> Fixture {
+        let temp = TempDir::new().unwrap();
+        let registry = temp.path().join("registry.json");
+        let manifest = temp.path().join("vault.manifest");
+        fs::write(&registry, b"registry-v1").unwrap();
+        fs::write(&manifest, b"manifest-v1").unwrap();
+
+        Fixture {
+            journal_dir: temp.path().join("journal"),
+            registry,
+            manifest,
+            _temp: temp,
+        }
+    }
+
+    fn writes(f: &Fixture) -> Vec<PendingWrite> {
+        vec![
+            PendingWrite::new(&f.registry, b"registry-v2".to_vec()),
+            PendingWrite::new(&f.manifest, b"manifest-v2".to_vec()),
+        ]
+    }
+
+    #[test]
+    fn test_apply_writes_all_files_and_clears_journal() {
+        let f = fixture();
+        let journal = MutationJournal::at(&f.journal_dir);
+
+        journal.apply("attach_key_to_vault", writes(&f)).unwrap();
+
+        assert_eq!(fs::read(&f.registry).unwrap(), b"registry-v2");
+        assert_eq!(fs::read(&f.manifest).unwrap(), b"manifest-v2");
+        assert!(journal.recover().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_plan_is_dry_run() {
+        let f = fixture();
+        let journal = MutationJournal::at(&f.journal_dir);
+
+        let plan = journal.plan("attach_key_to_vault", &writes(&f)).unwrap();
+
+        assert_eq!(plan.changed_files().count(), 2);
+        assert_eq!(fs::read(&f.registry).unwrap(), b"registry-v1");
+        assert!(!f.journal_dir.exists());
+    }
+
+    #[test]
+    fn test_unchanged_files_are_skipped() {
+        let f = fixture();
+        let journal = MutationJournal::at(&f.journal_dir);
+        let writes = vec![PendingWrite::new(&f.registry, b"registry-v1".to_vec())];
+
+        let plan = journal.apply("noop", writes).unwrap();
+
+        assert_eq!(plan.changed_files().count(), 0);
+        assert!(!f.journal_dir.exists());
+    }
+
+    #[test]
+    fn test_crash_between_writes_is_replayed() {
+        let f = fixture();
+        let crashing = MutationJournal::at(&f.journal_dir).with_fail_point(FailAfterWrites(1));
+
+        assert!(crashing.apply("attach_key_to_vault", writes(&f)).is_err());
+
+        // Torn state: registry updated, manifest not
+        assert_eq!(fs::read(&f.registry).unwrap(), b"registry-v2");
+        assert_eq!(fs::read(&f.manifest).unwrap(), b"manifest-v1");
+
+        let report = MutationJournal::at(&f.journal_dir).recover().unwrap();
+
+        assert_eq!(report.count(RecoveryAction::Replayed), 1);
+        assert_eq!(report.recovered[0].operation, "attach_key_to_vault");
+        assert_eq!(fs::read(&f.registry).unwrap(), b"registry-v2");
+        assert_eq!(fs::read(&f.manifest).unwrap(), b"manifest-v2");
+    }
+
+    #[test]
+    fn test_crash_with_lost_staging_is_rolled_back() {
+        let f = fixture();
+        let crashing = MutationJournal::at(&f.journal_dir).with_fail_point(FailAfterWrites(1));
+        assert!(crashing.apply("remove_key_from_vault", writes(&f)).is_err());
+
+        // Simulate staged contents lost with the crash
+        for entry in fs::read_dir(&f.journal_dir).unwrap() {
+            let path = entry.unwrap().path();
+            if path.is_dir() {
+                fs::remove_file(path.join(staged_name(1))).unwrap();
+            }
+        }
+
+        let report = MutationJournal::at(&f.journal_dir).recover().unwrap();
+
+        assert_eq!(report.count(RecoveryAction::RolledBack), 1);
+        assert_eq!(fs::read(&f.registry).unwrap(), b"registry-v1");
+        assert_eq!(fs::read(&f.manifest).unwrap(), b"manifest-v1");
+    }
+
+    #[test]
+    fn test_crash_before_first_write_leaves_files_consistent() {
+        let f = fixture();
+        let crashing = MutationJournal::at(&f.journal_dir).with_fail_point(FailAfterWrites(0));
+        assert!(crashing.apply("update_key_label", writes(&f)).is_err());
+
+        assert_eq!(fs::read(&f.registry).unwrap(), b"registry-v1");
+
+        MutationJournal::at(&f.journal_dir).recover().unwrap();
+
+        assert_eq!(fs::read(&f.registry).unwrap(), b"registry-v2");
+        assert_eq!(fs::read(&f.manifest).unwrap(), b"manifest-v2");
+    }
+
+    #[test]
+    fn test_rollback_removes_files_that_did_not_exist() {
+        let f = fixture();
+        let new_file = f.journal_dir.parent().unwrap().join("new.manifest");
+        let crashing = MutationJournal::at(&f.journal_dir).with_fail_point(FailAfterWrites(1));
+        let writes = vec![
+            PendingWrite::new(&new_file, b"created".to_vec()),
+            PendingWrite::new(&f.registry, b"registry-v2".to_vec()),
+        ];
+        assert!(crashing.apply("create", writes).is_err());
+        assert!(new_file.exists());
+
+        for entry in fs::read_dir(&f.journal_dir).unwrap() {
+            let path = entry.unwrap().path();
+            if path.is_dir() {
+                fs::remove_dir_all(path).unwrap();
+            }
+        }
+
+        MutationJournal::at(&f.journal_dir).recover().unwrap();
+
+        assert!(!new_file.exists());
+        assert_eq!(fs::read(&f.registry).unwrap(), b"registry-v1");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_apply_preserves_target_permissions() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let f = fixture();
+        fs::set_permissions(&f.registry, fs::Permissions::from_mode(0o600)).unwrap();
+
+        MutationJournal::at(&f.journal_dir)
+            .apply("attach_key_to_vault", writes(&f))
+            .unwrap();
+
+        let mode = fs::metadata(&f.registry).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+}
@@ -1,7 +1,38 @@
 //! I/O utilities for safe file operations
 
 pub mod atomic_write;
+pub mod durable_sync;
+pub mod fs_capabilities;
+pub mod io_priority;
+pub mod journal;
+pub mod os_protection;
+pub mod safe_overwrite;
+pub mod secure_delete;
 pub mod secure_temp;
+pub mod store_lock;
+pub mod volume_info;
 
-pub use atomic_write::{atomic_write, atomic_write_sync};
+pub use atomic_write::{
+    atomic_write, atomic_write_sync, recover_interrupted_swap, recover_interrupted_swaps,
+};
+pub use durable_sync::{DurableStorage, SystemDurableStorage};
+pub use fs_capabilities::{
+    CloudSyncProvider, FilesystemKind, FsCapabilities, capabilities_for, detect_cloud_sync,
+    mount_type,
+};
+pub use io_priority::{
+    IoPacer, OperationPriority, PacingHook, PriorityRegistration, SleepPacing, operation_priority,
+    register_operation_priority, set_operation_priority,
+};
+pub use journal::{
+    MutationJournal, MutationPlan, PendingWrite, PlannedFileChange, RecoveryAction, RecoveryReport,
+};
+pub use os_protection::{is_os_protected, set_os_protection};
+pub use safe_overwrite::{ArchiveOverwrite, ReplacedFile};
+pub use secure_delete::{
+    DeleteHonesty, DeleteStrategy, MediaInfo, MediaProbe, SecureDeleteCapability,
+    SecureDeleteService, SystemMediaProbe, get_secure_delete_capability, select_strategy,
+};
 pub use secure_temp::SecureTempFile;
+pub use store_lock::{STORE_LOCK_STALE_SECS, StoreLock, StoreLockError, StoreLockOwner};
+pub use volume_info::{SystemVolumeProbe, VolumeInfo, VolumeInfoService, VolumeKind, VolumeProbe};
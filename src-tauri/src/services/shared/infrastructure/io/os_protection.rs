@@ -0,0 +1,156 @@
+//! OS-level write protection for individual files
+//!
+//! Best effort only, on top of the app's own checks:
+//! - macOS: the user immutable flag (`chflags uchg`)
+//! - Windows: the read-only attribute
+//! - Linux: the immutable attribute (`chattr +i`), which needs
+//!   CAP_LINUX_IMMUTABLE and a filesystem that supports it
+//!
+//! Failures are logged rather than returned; `is_os_protected` reports what
+//! actually took effect.
+
+use std::path::Path;
+use tracing::debug;
+
+/// Turn OS-level protection on or off, returning whether it's now in effect
+pub fn set_os_protection(path: &Path, protect: bool) -> bool {
+    if let Err(e) = platform::set(path, protect) {
+        debug!(path = %path.display(), protect, error = %e, "OS protection not changed");
+    }
+    is_os_protected(path)
+}
+
+/// Whether the OS currently blocks changes to `path`
+pub fn is_os_protected(path: &Path) -> bool {
+    path.exists() && platform::is_set(path)
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use std::io;
+    use std::os::macos::fs::MetadataExt;
+    use std::path::Path;
+    use std::process::Command;
+
+    /// `UF_IMMUTABLE` from sys/stat.h
+    const UF_IMMUTABLE: u32 = 0x0000_0002;
+
+    pub fn set(path: &Path, protect: bool) -> io::Result<()> {
+        let flag = if protect { "uchg" } else { "nouchg" };
+        let status = Command::new("chflags").arg(flag).arg(path).status()?;
+        if status.success() {
+            Ok(())
+        } else {
+            Err(io::Error::other(format!("chflags exited with {status}")))
+        }
+    }
+
+    pub fn is_set(path: &Path) -> bool {
+        std::fs::metadata(path).is_ok_and(|m| m.st_flags() & UF_IMMUTABLE != 0)
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use std::io;
+    use std::path::Path;
+
+    pub fn set(path: &Path, protect: bool) -> io::Result<()> {
+        let mut permissions = std::fs::metadata(path)?.permissions();
+        permissions.set_readonly(protect);
+        std::fs::set_permissions(path, permissions)
+    }
+
+    pub fn is_set(path: &Path) -> bool {
+        std::fs::metadata(path).is_ok_and(|m| m.permissions().readonly())
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+    use std::io;
+    use std::path::Path;
+    use std::process::{Command, Stdio};
+
+    pub fn set(path: &Path, protect: bool) -> io::Result<()> {
+        let flag = if protect { "+i" } else { "-i" };
+        let status = Command::new("chattr")
+            .arg(flag)
+            .arg(path)
+            .stderr(Stdio::null())
+            .status()?;
+        if status.success() {
+            Ok(())
+        } else {
+            Err(io::Error::other(format!("chattr exited with {status}")))
+        }
+    }
+
+    /// `lsattr -d` prints e.g. `----i---------e------- /path`
+    pub fn is_set(path: &Path) -> bool {
+        Command::new("lsattr")
+            .arg("-d")
+            .arg(path)
+            .stderr(Stdio::null())
+            .output()
+            .ok()
+            .filter(|output| output.status.success())
+            .and_then(|output| {
+                let stdout = String::from_utf8_lossy(&output.stdout).into_owned();
+                stdout
+                    .split_whitespace()
+                    .next()
+                    .map(|flags| flags.contains('i'))
+            })
+            .unwrap_or(false)
+    }
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
+mod platform {
+    use std::io;
+    use std::path::Path;
+
+    pub fn set(_path: &Path, _protect: bool) -> io::Result<()> {
+        Err(io::Error::from(io::ErrorKind::Unsupported))
+    }
+
+    pub fn is_set(_path: &Path) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_protection_applies_and_clears() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("will.age");
+        std::fs::write(&path, b"archive").unwrap();
+        assert!(!is_os_protected(&path));
+
+        let protected = set_os_protection(&path, true);
+        assert_eq!(protected, is_os_protected(&path));
+        // Always available to the file's owner on macOS and Windows; Linux
+        // needs CAP_LINUX_IMMUTABLE, so it may silently not apply
+        if cfg!(any(target_os = "macos", target_os = "windows")) {
+            assert!(protected);
+        }
+        if protected {
+            assert!(std::fs::write(&path, b"changed").is_err());
+        }
+
+        assert!(!set_os_protection(&path, false));
+        std::fs::write(&path, b"changed").unwrap();
+    }
+
+    #[test]
+    fn test_missing_file_is_not_protected() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("missing.age");
+        assert!(!set_os_protection(&path, true));
+    }
+}
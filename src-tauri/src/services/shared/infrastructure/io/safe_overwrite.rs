@@ -0,0 +1,444 @@
+//! Verified overwrite for encrypted archives
+//!
+//! Re-encrypting a vault writes over the previous `.age` archive. A plain
+//! write that fails halfway would destroy the only backup, so outputs go
+//! through two phases:
+//!
+//! 1. `stage()` writes each output to a temporary sibling, fsyncs it, streams
+//!    it back, and checks size, SHA-256, and that the age header parses
+//! 2. `commit()` keeps a hard-linked copy of each existing target, then
+//!    renames the staged files over their targets. If any rename fails, the
+//!    targets already replaced are rolled back from their copies, so a set of
+//!    outputs (e.g. backup and shared bundles) is replaced all or nothing
+//!
+//! The target path always holds either the old or the new archive. Staged
+//! files left over from a failure are removed on drop.
+
+use crate::error::StorageError;
+use crate::services::crypto::infrastructure::archive_reader;
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::io::{BufReader, Write};
+use std::path::{Path, PathBuf};
+use tracing::{debug, info, warn};
+
+/// Injected failure points for tests
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(not(test), allow(dead_code))]
+pub(crate) enum OverwriteFailPoint {
+    /// Stop halfway through writing the staged file with this index
+    PartialWrite(usize),
+    /// Fail before the rename with this index (after earlier renames succeeded)
+    BeforeRename(usize),
+}
+
+/// An output that was written over its target
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReplacedFile {
+    pub path: PathBuf,
+    pub size: u64,
+    /// SHA-256 of the new contents, hex encoded
+    pub sha256: String,
+    /// Whether a previous file existed at `path`
+    pub replaced_existing: bool,
+    /// SHA-256 of the file that was replaced
+    pub previous_sha256: Option<String>,
+}
+
+#[derive(Debug)]
+struct StagedFile {
+    target: PathBuf,
+    staging: PathBuf,
+    size: u64,
+    sha256: String,
+}
+
+/// A set of archive outputs replaced all or nothing
+#[derive(Debug, Default)]
+pub struct ArchiveOverwrite {
+    staged: Vec<StagedFile>,
+    #[cfg(test)]
+    fail_point: Option<OverwriteFailPoint>,
+}
+
+impl ArchiveOverwrite {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[cfg(test)]
+    pub(crate) fn with_fail_point(mut self, fail_point: OverwriteFailPoint) -> Self {
+        self.fail_point = Some(fail_point);
+        self
+    }
+
+    #[cfg(test)]
+    fn hits_fail_point(&self, point: OverwriteFailPoint) -> bool {
+        self.fail_point == Some(point)
+    }
+
+    #[cfg(not(test))]
+    fn hits_fail_point(&self, _point: OverwriteFailPoint) -> bool {
+        false
+    }
+
+    /// Write `data` beside `target` and verify it, without touching `target`
+    pub fn stage(&mut self, target: &Path, data: &[u8]) -> Result<(), StorageError> {
+        let staging = sibling_path(target, "partial")?;
+        let index = self.staged.len();
+        let sha256 = hash_bytes(data);
+
+        if let Err(e) = self.write_and_verify(&staging, data, &sha256, index) {
+            let _ = fs::remove_file(&staging);
+            warn!(target = %target.display(), error = %e, "Staging archive failed");
+            return Err(e);
+        }
+
+        debug!(target = %target.display(), size = data.len(), "Staged archive");
+        self.staged.push(StagedFile {
+            target: target.to_path_buf(),
+            staging,
+            size: data.len() as u64,
+            sha256,
+        });
+        Ok(())
+    }
+
+    fn write_and_verify(
+        &self,
+        staging: &Path,
+        data: &[u8],
+        sha256: &str,
+        index: usize,
+    ) -> Result<(), StorageError> {
+        let write_failed = |source| StorageError::FileWriteFailed {
+            path: staging.to_path_buf(),
+            source,
+        };
+
+        let mut file = fs::File::create(staging).map_err(write_failed)?;
+        if self.hits_fail_point(OverwriteFailPoint::PartialWrite(index)) {
+            file.write_all(&data[..data.len() / 2])
+                .map_err(write_failed)?;
+            return Err(write_failed(std::io::Error::other(
+                "Injected failure point",
+            )));
+        }
+        file.write_all(data).map_err(write_failed)?;
+        file.sync_all().map_err(write_failed)?;
+        drop(file);
+
+        let read_failed = |source| StorageError::FileReadFailed {
+            path: staging.to_path_buf(),
+            source,
+        };
+        let (written_size, written_sha256) = hash_file(staging).map_err(read_failed)?;
+        if written_size != data.len() as u64 || written_sha256 != sha256 {
+            return Err(StorageError::FileCorruption(format!(
+                "Staged archive {} doesn't match what was written",
+                staging.display()
+            )));
+        }
+        // Armored archives are checked through their decoded header
+        let staged = fs::File::open(staging).map_err(read_failed)?;
+        let header = archive_reader(BufReader::new(staged)).and_then(|reader| {
+            age::Decryptor::new(reader).map_err(|e| std::io::Error::other(e.to_string()))
+        });
+        if let Err(e) = header {
+            return Err(StorageError::FileCorruption(format!(
+                "Staged archive {} has an unreadable age header: {}",
+                staging.display(),
+                e
+            )));
+        }
+        Ok(())
+    }
+
+    /// Replace every target with its staged file, or none of them
+    pub fn commit(mut self) -> Result<Vec<ReplacedFile>, StorageError> {
+        let staged = std::mem::take(&mut self.staged);
+        let mut done: Vec<(ReplacedFile, Option<PathBuf>)> = Vec::new();
+
+        for (index, file) in staged.iter().enumerate() {
+            if let Err(e) = self.replace_one(index, file, &mut done) {
+                warn!(
+                    target = %file.target.display(),
+                    error = %e,
+                    "Archive replace failed, rolling back"
+                );
+                rollback(&done);
+                for file in &staged {
+                    let _ = fs::remove_file(&file.staging);
+                }
+                return Err(e);
+            }
+        }
+
+        let mut replaced = Vec::with_capacity(done.len());
+        for (file, previous_copy) in done {
+            if let Some(copy) = previous_copy
+                && let Err(e) = fs::remove_file(&copy)
+            {
+                warn!(
+                    path = %copy.display(),
+                    error = %e,
+                    "Failed to remove previous archive copy"
+                );
+            }
+            if file.replaced_existing {
+                info!(
+                    path = %file.path.display(),
+                    previous_sha256 = file.previous_sha256.as_deref().unwrap_or_default(),
+                    sha256 = %file.sha256,
+                    "Replaced existing archive"
+                );
+            }
+            replaced.push(file);
+        }
+        Ok(replaced)
+    }
+
+    fn replace_one(
+        &self,
+        index: usize,
+        file: &StagedFile,
+        done: &mut Vec<(ReplacedFile, Option<PathBuf>)>,
+    ) -> Result<(), StorageError> {
+        let previous_sha256 = match hash_file(&file.target) {
+            Ok((_, sha256)) => Some(sha256),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+            Err(source) => {
+                return Err(StorageError::FileReadFailed {
+                    path: file.target.clone(),
+                    source,
+                });
+            }
+        };
+
+        let previous_copy = match previous_sha256 {
+            Some(_) => Some(keep_copy(&file.target)?),
+            None => None,
+        };
+
+        let entry = ReplacedFile {
+            path: file.target.clone(),
+            size: file.size,
+            sha256: file.sha256.clone(),
+            replaced_existing: previous_sha256.is_some(),
+            previous_sha256,
+        };
+
+        if self.hits_fail_point(OverwriteFailPoint::BeforeRename(index)) {
+            done.push((entry, previous_copy));
+            return Err(StorageError::IoError(std::io::Error::other(
+                "Injected failure point",
+            )));
+        }
+
+        let renamed = fs::rename(&file.staging, &file.target);
+        // Record before checking so rollback also cleans up this file's copy
+        done.push((entry, previous_copy));
+        renamed.map_err(|source| StorageError::FileWriteFailed {
+            path: file.target.clone(),
+            source,
+        })
+    }
+}
+
+impl Drop for ArchiveOverwrite {
+    fn drop(&mut self) {
+        for file in &self.staged {
+            if let Err(e) = fs::remove_file(&file.staging)
+                && e.kind() != std::io::ErrorKind::NotFound
+            {
+                warn!(
+                    path = %file.staging.display(),
+                    error = %e,
+                    "Failed to remove staged archive"
+                );
+            }
+        }
+    }
+}
+
+/// Restore replaced targets from their copies, newest first
+fn rollback(done: &[(ReplacedFile, Option<PathBuf>)]) {
+    for (file, previous_copy) in done.iter().rev() {
+        let result = match previous_copy {
+            Some(copy) => fs::rename(copy, &file.path),
+            None => match fs::remove_file(&file.path) {
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+                other => other,
+            },
+        };
+        if let Err(e) = result {
+            warn!(path = %file.path.display(), error = %e, "Failed to roll back archive");
+        }
+    }
+}
+
+/// Keep the current target reachable under a sibling name
+///
+/// A hard link costs nothing and leaves the target in place; copy when the
+/// filesystem doesn't support links.
+fn keep_copy(target: &Path) -> Result<PathBuf, StorageError> {
+    let copy = sibling_path(target, "previous")?;
+    if fs::hard_link(target, &copy).is_err() {
+        fs::copy(target, &copy).map_err(|source| StorageError::FileWriteFailed {
+            path: copy.clone(),
+            source,
+        })?;
+    }
+    Ok(copy)
+}
+
+/// Hidden sibling of `target` (same directory, so renames stay atomic)
+fn sibling_path(target: &Path, suffix: &str) -> Result<PathBuf, StorageError> {
+    let file_name = target
+        .file_name()
+        .ok_or_else(|| StorageError::InvalidFormat {
+            path: target.to_path_buf(),
+            message: "Archive path has no file name".to_string(),
+        })?;
+    Ok(target.with_file_name(format!(
+        ".{}.{}.{}",
+        file_name.to_string_lossy(),
+        uuid::Uuid::new_v4().simple(),
+        suffix
+    )))
+}
+
+fn hash_bytes(data: &[u8]) -> String {
+    hex::encode(Sha256::digest(data))
+}
+
+/// Size and SHA-256 of the file at `path`, read in chunks
+fn hash_file(path: &Path) -> std::io::Result<(u64, String)> {
+    let mut hasher = Sha256::new();
+    let size = std::io::copy(&mut fs::File::open(path)?, &mut hasher)?;
+    Ok((size, hex::encode(hasher.finalize())))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn archive(payload: &[u8]) -> Vec<u8> {
+        let recipient = age::x25519::Identity::generate().to_public();
+        age::encrypt(&recipient, payload).unwrap()
+    }
+
+    /// Only the given files remain in `dir` (no staged or copied leftovers)
+    fn assert_dir_contains(dir: &Path, expected: &[&str]) {
+        let mut names: Vec<String> = fs::read_dir(dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        names.sort();
+        assert_eq!(names, expected);
+    }
+
+    #[test]
+    fn test_replaces_existing_archive_with_previous_hash() {
+        let temp_dir = TempDir::new().unwrap();
+        let target = temp_dir.path().join("Vault.age");
+        let old = archive(b"old");
+        let new = archive(b"new");
+        fs::write(&target, &old).unwrap();
+
+        let mut overwrite = ArchiveOverwrite::new();
+        overwrite.stage(&target, &new).unwrap();
+        // Staging leaves the old archive in place
+        assert_eq!(fs::read(&target).unwrap(), old);
+
+        let replaced = overwrite.commit().unwrap();
+        assert_eq!(fs::read(&target).unwrap(), new);
+        assert!(replaced[0].replaced_existing);
+        assert_eq!(replaced[0].previous_sha256, Some(hash_bytes(&old)));
+        assert_eq!(replaced[0].sha256, hash_bytes(&new));
+        assert_dir_contains(temp_dir.path(), &["Vault.age"]);
+    }
+
+    #[test]
+    fn test_new_archive_reports_nothing_replaced() {
+        let temp_dir = TempDir::new().unwrap();
+        let target = temp_dir.path().join("Vault.age");
+
+        let mut overwrite = ArchiveOverwrite::new();
+        overwrite.stage(&target, &archive(b"new")).unwrap();
+        let replaced = overwrite.commit().unwrap();
+
+        assert!(!replaced[0].replaced_existing);
+        assert_eq!(replaced[0].previous_sha256, None);
+    }
+
+    #[test]
+    fn test_partial_write_keeps_old_archive() {
+        let temp_dir = TempDir::new().unwrap();
+        let target = temp_dir.path().join("Vault.age");
+        let old = archive(b"old");
+        fs::write(&target, &old).unwrap();
+
+        let mut overwrite =
+            ArchiveOverwrite::new().with_fail_point(OverwriteFailPoint::PartialWrite(0));
+        assert!(overwrite.stage(&target, &archive(b"new")).is_err());
+        drop(overwrite);
+
+        assert_eq!(fs::read(&target).unwrap(), old);
+        assert_dir_contains(temp_dir.path(), &["Vault.age"]);
+    }
+
+    #[test]
+    fn test_unverifiable_output_is_rejected() {
+        let temp_dir = TempDir::new().unwrap();
+        let target = temp_dir.path().join("Vault.age");
+        let old = archive(b"old");
+        fs::write(&target, &old).unwrap();
+
+        let mut overwrite = ArchiveOverwrite::new();
+        let result = overwrite.stage(&target, b"not an age archive");
+        assert!(matches!(result, Err(StorageError::FileCorruption(_))));
+        assert_eq!(fs::read(&target).unwrap(), old);
+        assert_dir_contains(temp_dir.path(), &["Vault.age"]);
+    }
+
+    #[test]
+    fn test_failure_between_renames_restores_whole_set() {
+        let temp_dir = TempDir::new().unwrap();
+        let names = ["Vault.age", "Vault-shared.age", "Vault.part3.age"];
+        let targets: Vec<PathBuf> = names.iter().map(|n| temp_dir.path().join(n)).collect();
+        let old: Vec<Vec<u8>> = (0..2).map(|i| archive(&[i])).collect();
+        fs::write(&targets[0], &old[0]).unwrap();
+        fs::write(&targets[1], &old[1]).unwrap();
+
+        // Third output is new, so rollback must remove it rather than restore it
+        let mut overwrite =
+            ArchiveOverwrite::new().with_fail_point(OverwriteFailPoint::BeforeRename(2));
+        for target in &targets {
+            overwrite.stage(target, &archive(b"new")).unwrap();
+        }
+        assert!(overwrite.commit().is_err());
+
+        assert_eq!(fs::read(&targets[0]).unwrap(), old[0]);
+        assert_eq!(fs::read(&targets[1]).unwrap(), old[1]);
+        assert!(!targets[2].exists());
+        assert_dir_contains(temp_dir.path(), &["Vault-shared.age", "Vault.age"]);
+    }
+
+    #[test]
+    fn test_failure_before_first_rename_touches_nothing() {
+        let temp_dir = TempDir::new().unwrap();
+        let target = temp_dir.path().join("Vault.age");
+        let old = archive(b"old");
+        fs::write(&target, &old).unwrap();
+
+        let mut overwrite =
+            ArchiveOverwrite::new().with_fail_point(OverwriteFailPoint::BeforeRename(0));
+        overwrite.stage(&target, &archive(b"new")).unwrap();
+        assert!(overwrite.commit().is_err());
+
+        assert_eq!(fs::read(&target).unwrap(), old);
+        assert_dir_contains(temp_dir.path(), &["Vault.age"]);
+    }
+}
@@ -0,0 +1,494 @@
+//! Secure deletion, and what it actually guarantees
+//!
+//! Overwriting a file before unlinking it only destroys its contents when the
+//! writes land on the blocks that held them. That holds for a spinning disk
+//! under a simple filesystem. It doesn't hold on SSDs and flash, where wear
+//! leveling remaps writes; on copy-on-write filesystems (APFS, btrfs, ZFS),
+//! where writes go to new blocks and snapshots keep old ones; or on network
+//! shares and cloud-sync folders, where another machine keeps its own copies.
+//!
+//! `SecureDeleteService` classifies the location first and uses the strategy
+//! that does the most good there, and every deletion reports an honesty level
+//! so callers can tell the user what was achieved rather than implying more.
+
+use super::fs_capabilities::{
+    CloudSyncProvider, FilesystemKind, detect_cloud_sync, mount_key, mount_type,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::Path;
+use std::sync::{Arc, Mutex, MutexGuard, OnceLock};
+use tracing::debug;
+
+/// Filesystems that never write in place
+const COPY_ON_WRITE_FILESYSTEMS: &[&str] = &["apfs", "btrfs", "zfs", "bcachefs", "refs", "nilfs2"];
+
+/// Filesystems held only in memory
+const MEMORY_FILESYSTEMS: &[&str] = &["tmpfs", "ramfs"];
+
+/// How a file is deleted
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, specta::Type)]
+#[serde(rename_all = "snake_case")]
+pub enum DeleteStrategy {
+    /// Zero the contents and sync, then unlink
+    Overwrite,
+    /// Truncate and sync so the blocks are released (and trimmed where the
+    /// device discards), then unlink
+    UnlinkWithTrim,
+    /// Unlink only; overwriting wouldn't reach the stored data
+    Unlink,
+}
+
+/// How far a deletion can be trusted to have destroyed the data
+///
+/// Ordered weakest first, so the level of several deletions is their minimum.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, specta::Type,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum DeleteHonesty {
+    /// Copies outside this machine's control survive (file server, sync client)
+    NotPossible,
+    /// The data is gone from the file, but the media may keep old blocks
+    BestEffort,
+    /// The data can't be read back from the media
+    Effective,
+}
+
+/// What deleting a file at a location does
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, specta::Type)]
+pub struct SecureDeleteCapability {
+    pub strategy: DeleteStrategy,
+    pub honesty: DeleteHonesty,
+    /// Mount type, when it could be determined
+    pub filesystem: Option<String>,
+    /// Why the honesty level is what it is, for the user
+    pub explanation: String,
+}
+
+/// The storage under a location, as far as deletion cares
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MediaInfo {
+    /// Mount type (`ext4`, `apfs`, ...)
+    pub fs_type: Option<String>,
+    /// Spinning disk (true) or SSD/flash (false)
+    pub rotational: Option<bool>,
+    /// The device accepts TRIM/discard
+    pub discard: Option<bool>,
+    pub cloud_sync: Option<CloudSyncProvider>,
+}
+
+/// Source of media information; the system one is replaced in tests
+pub trait MediaProbe: Send + Sync {
+    fn media_info(&self, dir: &Path) -> MediaInfo;
+}
+
+/// Reads media information from the OS, once per mount
+#[derive(Debug, Default)]
+pub struct SystemMediaProbe;
+
+impl MediaProbe for SystemMediaProbe {
+    fn media_info(&self, dir: &Path) -> MediaInfo {
+        let key = mount_key(dir);
+        let cached = lock_cache().get(&key).cloned();
+        let mut info = cached.unwrap_or_else(|| {
+            let (rotational, discard) = block_device_traits(dir);
+            let info = MediaInfo {
+                fs_type: mount_type(dir),
+                rotational,
+                discard,
+                cloud_sync: None,
+            };
+            lock_cache().insert(key, info.clone());
+            info
+        });
+        // Sync folders are per directory, not per mount
+        info.cloud_sync = detect_cloud_sync(dir);
+        info
+    }
+}
+
+fn lock_cache() -> MutexGuard<'static, HashMap<String, MediaInfo>> {
+    static CACHE: OnceLock<Mutex<HashMap<String, MediaInfo>>> = OnceLock::new();
+    CACHE
+        .get_or_init(Mutex::default)
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+}
+
+/// Rotational and discard flags of the block device holding `dir`
+///
+/// Partitions have no `queue` of their own; the whole disk's is used.
+#[cfg(target_os = "linux")]
+fn block_device_traits(dir: &Path) -> (Option<bool>, Option<bool>) {
+    use std::os::unix::fs::MetadataExt;
+
+    let Ok(dev) = fs::metadata(dir).map(|metadata| metadata.dev()) else {
+        return (None, None);
+    };
+    let major = ((dev >> 32) & 0xffff_f000) | ((dev >> 8) & 0x0000_0fff);
+    let minor = ((dev >> 12) & 0xffff_ff00) | (dev & 0x0000_00ff);
+    let device = std::path::PathBuf::from(format!("/sys/dev/block/{}:{}", major, minor));
+    let read_flag = |name: &str| {
+        [device.join("queue"), device.join("../queue")]
+            .iter()
+            .find_map(|queue| fs::read_to_string(queue.join(name)).ok())
+            .and_then(|value| value.trim().parse::<u64>().ok())
+    };
+    (
+        read_flag("rotational").map(|value| value == 1),
+        read_flag("discard_max_bytes").map(|value| value > 0),
+    )
+}
+
+#[cfg(not(target_os = "linux"))]
+fn block_device_traits(_dir: &Path) -> (Option<bool>, Option<bool>) {
+    (None, None)
+}
+
+/// Pick the deletion strategy for the storage described by `media`
+pub fn select_strategy(media: &MediaInfo) -> SecureDeleteCapability {
+    let fs_type = media.fs_type.as_deref().map(str::to_ascii_lowercase);
+    let capability = |strategy, honesty, explanation: String| SecureDeleteCapability {
+        strategy,
+        honesty,
+        filesystem: media.fs_type.clone(),
+        explanation,
+    };
+    let trim_or_unlink = if media.discard == Some(true) {
+        DeleteStrategy::UnlinkWithTrim
+    } else {
+        DeleteStrategy::Unlink
+    };
+
+    if let Some(provider) = media.cloud_sync {
+        return capability(
+            DeleteStrategy::Unlink,
+            DeleteHonesty::NotPossible,
+            format!(
+                "This folder is synced by {}, which keeps deleted files and earlier versions \
+                 on its servers",
+                provider.name()
+            ),
+        );
+    }
+    let Some(fs_type) = fs_type else {
+        return capability(
+            DeleteStrategy::Overwrite,
+            DeleteHonesty::BestEffort,
+            "The storage type couldn't be determined. Files are overwritten before removal, \
+             which may not reach every copy on SSDs"
+                .to_string(),
+        );
+    };
+
+    if FilesystemKind::from_mount_type(&fs_type) == FilesystemKind::Network {
+        return capability(
+            DeleteStrategy::Unlink,
+            DeleteHonesty::NotPossible,
+            "This is a network share. The file server decides what happens to deleted data \
+             and may keep snapshots or backups"
+                .to_string(),
+        );
+    }
+    if MEMORY_FILESYSTEMS.contains(&fs_type.as_str()) {
+        return capability(
+            DeleteStrategy::Unlink,
+            DeleteHonesty::Effective,
+            "This location is held in memory; nothing is written to disk".to_string(),
+        );
+    }
+    if COPY_ON_WRITE_FILESYSTEMS.contains(&fs_type.as_str()) {
+        return capability(
+            trim_or_unlink,
+            DeleteHonesty::BestEffort,
+            format!(
+                "{} is a copy-on-write filesystem. Overwriting writes to new blocks, and \
+                 snapshots or old blocks can keep deleted data until they are reused",
+                fs_type
+            ),
+        );
+    }
+
+    match media.rotational {
+        Some(true) => capability(
+            DeleteStrategy::Overwrite,
+            DeleteHonesty::Effective,
+            "Files are overwritten in place on this hard disk before removal".to_string(),
+        ),
+        Some(false) if media.discard == Some(true) => capability(
+            DeleteStrategy::UnlinkWithTrim,
+            DeleteHonesty::BestEffort,
+            "This is an SSD. Its blocks are released for TRIM on removal, but wear leveling \
+             may keep old copies until the drive erases them"
+                .to_string(),
+        ),
+        Some(false) => capability(
+            DeleteStrategy::Overwrite,
+            DeleteHonesty::BestEffort,
+            "This is flash storage without TRIM. Files are overwritten before removal, but \
+             wear leveling may keep old copies"
+                .to_string(),
+        ),
+        None => capability(
+            DeleteStrategy::Overwrite,
+            DeleteHonesty::BestEffort,
+            "The drive type couldn't be determined. Files are overwritten before removal, \
+             which may not reach every copy on SSDs"
+                .to_string(),
+        ),
+    }
+}
+
+/// Deletes files with the best strategy their location allows
+#[derive(Clone)]
+pub struct SecureDeleteService {
+    probe: Arc<dyn MediaProbe>,
+}
+
+impl std::fmt::Debug for SecureDeleteService {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SecureDeleteService")
+            .finish_non_exhaustive()
+    }
+}
+
+impl SecureDeleteService {
+    pub fn new() -> Self {
+        Self::with_probe(SystemMediaProbe)
+    }
+
+    pub fn with_probe(probe: impl MediaProbe + 'static) -> Self {
+        Self {
+            probe: Arc::new(probe),
+        }
+    }
+
+    /// What deleting `path` would do, for a file or directory that may not
+    /// exist yet
+    pub fn capability(&self, path: &Path) -> SecureDeleteCapability {
+        let dir = path
+            .ancestors()
+            .find(|ancestor| ancestor.is_dir())
+            .unwrap_or(Path::new("."));
+        select_strategy(&self.probe.media_info(dir))
+    }
+
+    /// Delete one file; symlinks and other non-regular files are only unlinked
+    ///
+    /// # Errors
+    /// The underlying I/O error; `NotFound` when there is nothing to delete.
+    pub fn delete_file(&self, path: &Path) -> io::Result<DeleteHonesty> {
+        let capability = self.capability(path);
+        erase(path, capability.strategy)?;
+        debug!(
+            path = %path.display(),
+            strategy = ?capability.strategy,
+            honesty = ?capability.honesty,
+            "Deleted file"
+        );
+        Ok(capability.honesty)
+    }
+
+    /// Delete a directory and everything in it, files first
+    ///
+    /// # Errors
+    /// The first I/O error; files already deleted stay deleted.
+    pub fn delete_dir_all(&self, dir: &Path) -> io::Result<DeleteHonesty> {
+        let capability = self.capability(dir);
+        for entry in walkdir::WalkDir::new(dir).contents_first(true) {
+            let entry = entry.map_err(io::Error::other)?;
+            if entry.file_type().is_dir() {
+                fs::remove_dir(entry.path())?;
+            } else {
+                erase(entry.path(), capability.strategy)?;
+            }
+        }
+        debug!(
+            dir = %dir.display(),
+            strategy = ?capability.strategy,
+            honesty = ?capability.honesty,
+            "Deleted directory"
+        );
+        Ok(capability.honesty)
+    }
+}
+
+impl Default for SecureDeleteService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// What deleting a file at `path` would do on this system
+pub fn get_secure_delete_capability(path: &Path) -> SecureDeleteCapability {
+    SecureDeleteService::new().capability(path)
+}
+
+fn erase(path: &Path, strategy: DeleteStrategy) -> io::Result<()> {
+    let metadata = fs::symlink_metadata(path)?;
+    if metadata.is_file() {
+        match strategy {
+            DeleteStrategy::Overwrite => overwrite(path, metadata.len())?,
+            DeleteStrategy::UnlinkWithTrim => {
+                let file = OpenOptions::new().write(true).open(path)?;
+                file.set_len(0)?;
+                file.sync_all()?;
+            }
+            DeleteStrategy::Unlink => {}
+        }
+    }
+    fs::remove_file(path)
+}
+
+/// Zero the file's contents and sync
+fn overwrite(path: &Path, len: u64) -> io::Result<()> {
+    let mut file = OpenOptions::new().write(true).open(path)?;
+    let zeros = vec![0u8; 8192];
+    let mut written = 0u64;
+    while written < len {
+        let chunk = zeros.len().min((len - written) as usize);
+        file.write_all(&zeros[..chunk])?;
+        written += chunk as u64;
+    }
+    file.sync_all()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    /// Modules whose deletions must go through `SecureDeleteService`
+    const ROUTED_MODULES: &[&str] = &[
+        "src/services/shared/infrastructure/io/secure_temp.rs",
+        "src/services/file/infrastructure/file_operations/staging.rs",
+        "src/services/crypto/application/services/cleanup_session_service.rs",
+        "src/services/crypto/application/services/archive_extraction_service.rs",
+        "src/services/crypto/application/services/decryption_orchestration_service.rs",
+        "src/services/vault/application/services/quarantine_service.rs",
+        "src/services/key_management/yubikey/infrastructure/pty/age_ops/decryption.rs",
+        "src/services/key_management/yubikey/infrastructure/pty/session_registry.rs",
+    ];
+
+    struct FixedProbe(MediaInfo);
+
+    impl MediaProbe for FixedProbe {
+        fn media_info(&self, _dir: &Path) -> MediaInfo {
+            self.0.clone()
+        }
+    }
+
+    fn media(fs_type: &str, rotational: Option<bool>, discard: Option<bool>) -> MediaInfo {
+        MediaInfo {
+            fs_type: Some(fs_type.to_string()),
+            rotational,
+            discard,
+            cloud_sync: None,
+        }
+    }
+
+    #[test]
+    fn test_strategy_selection() {
+        let hdd = select_strategy(&media("ext4", Some(true), Some(false)));
+        assert_eq!(hdd.strategy, DeleteStrategy::Overwrite);
+        assert_eq!(hdd.honesty, DeleteHonesty::Effective);
+
+        let ssd = select_strategy(&media("ext4", Some(false), Some(true)));
+        assert_eq!(ssd.strategy, DeleteStrategy::UnlinkWithTrim);
+        assert_eq!(ssd.honesty, DeleteHonesty::BestEffort);
+
+        let usb_stick = select_strategy(&media("exfat", Some(false), Some(false)));
+        assert_eq!(usb_stick.strategy, DeleteStrategy::Overwrite);
+        assert_eq!(usb_stick.honesty, DeleteHonesty::BestEffort);
+
+        // Copy-on-write wins over a spinning disk
+        let btrfs = select_strategy(&media("btrfs", Some(true), Some(false)));
+        assert_eq!(btrfs.strategy, DeleteStrategy::Unlink);
+        assert_eq!(btrfs.honesty, DeleteHonesty::BestEffort);
+        let apfs = select_strategy(&media("APFS", None, Some(true)));
+        assert_eq!(apfs.strategy, DeleteStrategy::UnlinkWithTrim);
+        assert_eq!(apfs.honesty, DeleteHonesty::BestEffort);
+
+        let smb = select_strategy(&media("cifs", Some(true), None));
+        assert_eq!(smb.strategy, DeleteStrategy::Unlink);
+        assert_eq!(smb.honesty, DeleteHonesty::NotPossible);
+
+        let tmpfs = select_strategy(&media("tmpfs", None, None));
+        assert_eq!(tmpfs.honesty, DeleteHonesty::Effective);
+
+        let unknown = select_strategy(&MediaInfo::default());
+        assert_eq!(unknown.strategy, DeleteStrategy::Overwrite);
+        assert_eq!(unknown.honesty, DeleteHonesty::BestEffort);
+        assert_eq!(unknown.filesystem, None);
+
+        let synced = select_strategy(&MediaInfo {
+            cloud_sync: Some(CloudSyncProvider::Dropbox),
+            ..media("ext4", Some(true), None)
+        });
+        assert_eq!(synced.honesty, DeleteHonesty::NotPossible);
+        assert!(synced.explanation.contains("Dropbox"));
+    }
+
+    #[test]
+    fn test_deletes_with_each_strategy() {
+        let temp = TempDir::new().unwrap();
+        for (fs_type, rotational, discard) in [
+            ("ext4", Some(true), None),
+            ("ext4", Some(false), Some(true)),
+            ("cifs", None, None),
+        ] {
+            let service =
+                SecureDeleteService::with_probe(FixedProbe(media(fs_type, rotational, discard)));
+            let expected = service.capability(temp.path()).honesty;
+
+            let file = temp.path().join("secret.txt");
+            fs::write(&file, b"secret").unwrap();
+            assert_eq!(service.delete_file(&file).unwrap(), expected);
+            assert!(!file.exists());
+
+            let dir = temp.path().join("staging");
+            fs::create_dir_all(dir.join("nested")).unwrap();
+            fs::write(dir.join("nested/a.txt"), b"a").unwrap();
+            fs::write(dir.join("b.txt"), b"b").unwrap();
+            assert_eq!(service.delete_dir_all(&dir).unwrap(), expected);
+            assert!(!dir.exists());
+        }
+
+        let missing = SecureDeleteService::new().delete_file(&temp.path().join("missing"));
+        assert_eq!(missing.unwrap_err().kind(), io::ErrorKind::NotFound);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_symlink_target_is_not_overwritten() {
+        let temp = TempDir::new().unwrap();
+        let target = temp.path().join("target.txt");
+        let link = temp.path().join("link");
+        fs::write(&target, b"keep me").unwrap();
+        std::os::unix::fs::symlink(&target, &link).unwrap();
+
+        let service = SecureDeleteService::with_probe(FixedProbe(media("ext4", Some(true), None)));
+        service.delete_file(&link).unwrap();
+        assert!(!link.exists());
+        assert_eq!(fs::read(&target).unwrap(), b"keep me");
+    }
+
+    #[test]
+    fn test_routed_modules_do_not_delete_directly() {
+        let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+        for module in ROUTED_MODULES {
+            let source = fs::read_to_string(root.join(module)).unwrap();
+            // Tests may set up and tear down fixtures however they like
+            let code = source.split("#[cfg(test)]").next().unwrap_or_default();
+            for raw in ["remove_file(", "remove_dir_all("] {
+                assert!(
+                    !code.contains(raw),
+                    "{module} calls {raw}...) directly; use SecureDeleteService"
+                );
+            }
+        }
+    }
+}
@@ -1,11 +1,10 @@
 //! Secure temporary file handling with proper cleanup
 //!
 //! Provides secure temporary files with restrictive permissions and
-//! secure deletion (overwrite before unlink).
+//! deletion through `SecureDeleteService`.
 
+use super::secure_delete::{DeleteHonesty, SecureDeleteService};
 use crate::error::StorageError;
-use std::fs::OpenOptions;
-use std::io::Write;
 use std::path::{Path, PathBuf};
 use tempfile::NamedTempFile;
 use tracing::{debug, info};
@@ -13,7 +12,7 @@
 /// Secure temporary file with automatic cleanup
 ///
 /// Creates temp file with 0600 permissions (owner-only).
-/// Provides secure_delete() for overwriting before deletion.
+/// Provides secure_delete() for deleting with the location's best strategy.
 pub struct SecureTempFile {
     inner: Option<NamedTempFile>,
     path: PathBuf,
@@ -61,67 +60,33 @@ pub fn path(&self) -> &Path {
 
     /// Securely delete the temporary file
     ///
-    /// Overwrites with zeros, syncs to disk, then deletes.
-    /// Prevents forensic recovery of sensitive data.
-    pub fn secure_delete(mut self) -> Result<(), StorageError> {
-        if let Some(temp) = self.inner.take() {
-            let path = temp.path().to_path_buf();
-
-            info!(path = %path.display(), "Securely deleting temp file");
-
-            // Get file size
-            let metadata = std::fs::metadata(&path).map_err(|e| StorageError::FileReadFailed {
-                path: path.clone(),
-                source: e,
-            })?;
-            let file_size = metadata.len();
-
-            // Close the NamedTempFile to release handle
-            let (_, temp_path) = temp.keep().map_err(|e| StorageError::FileWriteFailed {
-                path: path.clone(),
-                source: e.error,
-            })?;
+    /// Uses the strategy `SecureDeleteService` picks for the temp directory
+    /// and returns how far the deletion can be trusted. On SSDs and
+    /// copy-on-write filesystems old blocks may survive.
+    pub fn secure_delete(mut self) -> Result<DeleteHonesty, StorageError> {
+        let Some(temp) = self.inner.take() else {
+            // Nothing left on disk to delete
+            return Ok(DeleteHonesty::Effective);
+        };
+        let path = temp.path().to_path_buf();
 
-            // Overwrite with zeros
-            let mut file = OpenOptions::new()
-                .write(true)
-                .open(&temp_path)
-                .map_err(|e| StorageError::FileWriteFailed {
-                    path: temp_path.clone(),
-                    source: e,
-                })?;
-
-            let zero_buffer = vec![0u8; 8192];
-            let mut written = 0u64;
-
-            while written < file_size {
-                let to_write = std::cmp::min(zero_buffer.len() as u64, file_size - written);
-                file.write_all(&zero_buffer[..to_write as usize])
-                    .map_err(|e| StorageError::FileWriteFailed {
-                        path: temp_path.clone(),
-                        source: e,
-                    })?;
-                written += to_write;
-            }
-
-            // Force sync to disk
-            file.sync_all().map_err(|e| StorageError::FileWriteFailed {
-                path: temp_path.clone(),
-                source: e,
-            })?;
+        info!(path = %path.display(), "Securely deleting temp file");
 
-            drop(file);
+        // Close the NamedTempFile to release handle
+        let (_, temp_path) = temp.keep().map_err(|e| StorageError::FileWriteFailed {
+            path: path.clone(),
+            source: e.error,
+        })?;
 
-            // Delete file
-            std::fs::remove_file(&temp_path).map_err(|e| StorageError::FileWriteFailed {
+        let honesty = SecureDeleteService::new()
+            .delete_file(&temp_path)
+            .map_err(|e| StorageError::FileWriteFailed {
                 path: temp_path.clone(),
                 source: e,
             })?;
 
-            debug!(path = %temp_path.display(), size = file_size, "Secure deletion completed");
-        }
-
-        Ok(())
+        debug!(path = %temp_path.display(), ?honesty, "Secure deletion completed");
+        Ok(honesty)
     }
 }
 
@@ -0,0 +1,399 @@
+//! Advisory lock on a shared vault store
+//!
+//! Accounts sharing a vault store (see `path_management::shared_store`) read
+//! it freely but hold the store lock while changing it, so two accounts never
+//! rewrite the same manifest or index at once. The lock is the directory
+//! `<store>/locks/store.lock`, which `create_dir` makes atomically, holding
+//! `owner.json` with who took it and when they were last seen.
+//!
+//! `atomic_write` takes the lock for every write into the store. A holder
+//! that stopped updating its heartbeat, or whose process on this machine has
+//! exited, is stale and may be taken over with `StoreLock::take_over`.
+
+use crate::services::shared::infrastructure::device_identity::{get_hostname, get_username};
+use crate::services::shared::infrastructure::path_management::{
+    STORE_LOCKS_DIR, get_app_dir, get_shared_store_dir,
+};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use tracing::{debug, warn};
+
+const STORE_LOCK_DIR: &str = "store.lock";
+const OWNER_FILENAME: &str = "owner.json";
+
+/// A holder not seen for this long is stale
+pub const STORE_LOCK_STALE_SECS: i64 = 120;
+
+/// Who holds the store lock
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+pub struct StoreLockOwner {
+    pub user: String,
+    pub host: String,
+    pub pid: u32,
+    /// Data directory of the holder's profile
+    pub profile: String,
+    pub acquired_at: DateTime<Utc>,
+    pub heartbeat_at: DateTime<Utc>,
+}
+
+impl StoreLockOwner {
+    /// This process, working in the profile at `profile_dir`
+    pub fn for_profile(profile_dir: &Path) -> Self {
+        let now = Utc::now();
+        Self {
+            user: get_username().unwrap_or_else(|| "unknown".to_string()),
+            host: get_hostname().unwrap_or_else(|| "unknown".to_string()),
+            pid: std::process::id(),
+            profile: profile_dir.display().to_string(),
+            acquired_at: now,
+            heartbeat_at: now,
+        }
+    }
+
+    /// Whether both are this same process in the same profile
+    pub fn same_holder(&self, other: &Self) -> bool {
+        self.host == other.host && self.pid == other.pid && self.profile == other.profile
+    }
+
+    /// Whether the holder can be presumed gone at `now`
+    pub fn is_stale(&self, now: DateTime<Utc>) -> bool {
+        if now - self.heartbeat_at > Duration::seconds(STORE_LOCK_STALE_SECS) {
+            return true;
+        }
+        self.pid != 0
+            && get_hostname().is_some_and(|host| host == self.host)
+            && !process_alive(self.pid)
+    }
+
+    /// Stand-in for a lock directory whose owner file is missing or unreadable
+    ///
+    /// Either its holder is writing the file right now or crashed before
+    /// finishing it, so it's judged by the directory's age.
+    fn unknown(lock_dir: &Path) -> io::Result<Self> {
+        let modified: DateTime<Utc> = fs::metadata(lock_dir)?.modified()?.into();
+        Ok(Self {
+            user: "unknown".to_string(),
+            host: "unknown".to_string(),
+            pid: 0,
+            profile: String::new(),
+            acquired_at: modified,
+            heartbeat_at: modified,
+        })
+    }
+}
+
+#[cfg(unix)]
+fn process_alive(pid: u32) -> bool {
+    use nix::libc;
+
+    // Signal 0 checks the process exists without touching it
+    let Ok(pid) = libc::pid_t::try_from(pid) else {
+        return true;
+    };
+    if unsafe { libc::kill(pid, 0) } == 0 {
+        return true;
+    }
+    // Alive, but another account's process
+    io::Error::last_os_error().raw_os_error() == Some(libc::EPERM)
+}
+
+#[cfg(not(unix))]
+fn process_alive(_pid: u32) -> bool {
+    true
+}
+
+#[derive(Debug)]
+pub enum StoreLockError {
+    /// Another account or app instance holds the lock
+    InUse {
+        owner: StoreLockOwner,
+        stale: bool,
+    },
+    Io(io::Error),
+}
+
+impl fmt::Display for StoreLockError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InUse { owner, stale } => {
+                write!(
+                    f,
+                    "The shared vault store is in use by {} on {}",
+                    owner.user, owner.host
+                )?;
+                if *stale {
+                    write!(f, ", who hasn't been seen since {}", owner.heartbeat_at)?;
+                }
+                Ok(())
+            }
+            Self::Io(e) => write!(f, "Failed to lock the shared vault store: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for StoreLockError {}
+
+impl From<io::Error> for StoreLockError {
+    fn from(e: io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+/// The held store lock, released on drop
+#[derive(Debug)]
+pub struct StoreLock {
+    lock_dir: PathBuf,
+    owner: StoreLockOwner,
+    /// False when this process already held the lock, so the outer holder
+    /// releases it
+    releases: bool,
+}
+
+impl StoreLock {
+    /// Take the lock on the store at `store_dir` for the profile at `profile_dir`
+    pub fn acquire(store_dir: &Path, profile_dir: &Path) -> Result<Self, StoreLockError> {
+        let lock_dir = lock_dir(store_dir);
+        fs::create_dir_all(store_dir.join(STORE_LOCKS_DIR))?;
+        let owner = StoreLockOwner::for_profile(profile_dir);
+
+        match fs::create_dir(&lock_dir) {
+            Ok(()) => {
+                write_owner(&lock_dir, &owner)?;
+                debug!(store = %store_dir.display(), "Store lock acquired");
+                Ok(Self {
+                    lock_dir,
+                    owner,
+                    releases: true,
+                })
+            }
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => {
+                match Self::holder(store_dir)? {
+                    Some(held) if held.same_holder(&owner) => Ok(Self {
+                        lock_dir,
+                        owner: held,
+                        releases: false,
+                    }),
+                    Some(held) => Err(StoreLockError::InUse {
+                        stale: held.is_stale(Utc::now()),
+                        owner: held,
+                    }),
+                    // Released between our attempt and the check
+                    None => Self::acquire(store_dir, profile_dir),
+                }
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Take the lock from a stale holder
+    ///
+    /// Refuses with `InUse` while the holder is still around. The stale lock
+    /// is moved aside before it's removed, so two accounts taking over at
+    /// once can't both succeed.
+    pub fn take_over(store_dir: &Path, profile_dir: &Path) -> Result<Self, StoreLockError> {
+        let lock_dir = lock_dir(store_dir);
+        let held = match Self::holder(store_dir)? {
+            Some(held) if !held.same_holder(&StoreLockOwner::for_profile(profile_dir)) => held,
+            _ => return Self::acquire(store_dir, profile_dir),
+        };
+        if !held.is_stale(Utc::now()) {
+            return Err(StoreLockError::InUse {
+                owner: held,
+                stale: false,
+            });
+        }
+
+        let aside = lock_dir.with_extension(format!("stale-{}", std::process::id()));
+        match fs::rename(&lock_dir, &aside) {
+            Ok(()) => {}
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                return Self::acquire(store_dir, profile_dir);
+            }
+            Err(e) => return Err(e.into()),
+        }
+        let moved = read_owner(&aside)?;
+        if moved.as_ref() != Some(&held) {
+            // Someone else took over first and we moved their fresh lock
+            let _ = fs::rename(&aside, &lock_dir);
+            return Err(StoreLockError::InUse {
+                owner: moved.unwrap_or(held),
+                stale: false,
+            });
+        }
+        fs::remove_dir_all(&aside)?;
+        warn!(
+            user = %held.user,
+            host = %held.host,
+            heartbeat_at = %held.heartbeat_at,
+            "Took over stale store lock"
+        );
+        Self::acquire(store_dir, profile_dir)
+    }
+
+    /// Who holds the lock on the store at `store_dir`, if anyone
+    pub fn holder(store_dir: &Path) -> Result<Option<StoreLockOwner>, StoreLockError> {
+        Ok(read_owner(&lock_dir(store_dir))?)
+    }
+
+    /// The lock needed to write `path`, if it's in this profile's shared store
+    pub fn for_write(path: &Path) -> Result<Option<Self>, StoreLockError> {
+        let store_dir = match get_shared_store_dir() {
+            Ok(Some(store_dir)) if path.starts_with(&store_dir) => store_dir,
+            _ => return Ok(None),
+        };
+        let profile_dir = get_app_dir().map_err(io::Error::other)?;
+        Self::acquire(&store_dir, &profile_dir).map(Some)
+    }
+
+    pub fn owner(&self) -> &StoreLockOwner {
+        &self.owner
+    }
+
+    /// Record that the holder is still working, for long operations
+    ///
+    /// Fails with `InUse` if the lock was taken over meanwhile.
+    pub fn heartbeat(&mut self) -> Result<(), StoreLockError> {
+        if !self.releases {
+            return Ok(());
+        }
+        match read_owner(&self.lock_dir)? {
+            Some(held) if held.same_holder(&self.owner) => {
+                self.owner.heartbeat_at = Utc::now();
+                write_owner(&self.lock_dir, &self.owner)?;
+                Ok(())
+            }
+            Some(held) => Err(StoreLockError::InUse {
+                owner: held,
+                stale: false,
+            }),
+            None => Err(io::Error::new(io::ErrorKind::NotFound, "store lock was removed").into()),
+        }
+    }
+}
+
+impl Drop for StoreLock {
+    fn drop(&mut self) {
+        if !self.releases {
+            return;
+        }
+        // Leave a lock someone took over from us alone
+        match read_owner(&self.lock_dir) {
+            Ok(Some(held)) if held.same_holder(&self.owner) => {
+                if let Err(e) = fs::remove_dir_all(&self.lock_dir) {
+                    warn!(error = %e, "Failed to release store lock");
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn lock_dir(store_dir: &Path) -> PathBuf {
+    store_dir.join(STORE_LOCKS_DIR).join(STORE_LOCK_DIR)
+}
+
+fn read_owner(lock_dir: &Path) -> io::Result<Option<StoreLockOwner>> {
+    match fs::read_to_string(lock_dir.join(OWNER_FILENAME)) {
+        Ok(content) => match serde_json::from_str(&content) {
+            Ok(owner) => Ok(Some(owner)),
+            Err(_) => StoreLockOwner::unknown(lock_dir).map(Some),
+        },
+        Err(e) if e.kind() == io::ErrorKind::NotFound => match StoreLockOwner::unknown(lock_dir) {
+            Ok(owner) => Ok(Some(owner)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e),
+        },
+        Err(e) => Err(e),
+    }
+}
+
+/// Replace `owner.json` by rename so readers never see it half written
+fn write_owner(lock_dir: &Path, owner: &StoreLockOwner) -> io::Result<()> {
+    let json = serde_json::to_string_pretty(owner).map_err(io::Error::other)?;
+    let temp = lock_dir.join(format!("{OWNER_FILENAME}.{}.tmp", std::process::id()));
+    fs::write(&temp, json)?;
+    fs::rename(&temp, lock_dir.join(OWNER_FILENAME))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    /// A shared store and two profiles, as two OS accounts would have them
+    fn accounts() -> (TempDir, PathBuf, PathBuf, PathBuf) {
+        let temp_dir = TempDir::new().unwrap();
+        let store = temp_dir.path().join("Shared/Barqly");
+        let alice = temp_dir.path().join("alice/com.barqly.vault");
+        let bob = temp_dir.path().join("bob/com.barqly.vault");
+        for dir in [&store, &alice, &bob] {
+            fs::create_dir_all(dir).unwrap();
+        }
+        (temp_dir, store, alice, bob)
+    }
+
+    #[test]
+    fn test_second_profile_is_locked_out_but_can_read() {
+        let (_temp, store, alice, bob) = accounts();
+        let manifest = store.join("vaults/Family.manifest");
+        fs::create_dir_all(manifest.parent().unwrap()).unwrap();
+        fs::write(&manifest, b"{}").unwrap();
+
+        let held = StoreLock::acquire(&store, &alice).unwrap();
+        assert_eq!(fs::read(&manifest).unwrap(), b"{}");
+        match StoreLock::acquire(&store, &bob) {
+            Err(StoreLockError::InUse { owner, stale }) => {
+                assert_eq!(owner.profile, alice.display().to_string());
+                assert_eq!(owner.pid, std::process::id());
+                assert!(!stale);
+            }
+            other => panic!("expected InUse, got {other:?}"),
+        }
+        assert!(matches!(
+            StoreLock::take_over(&store, &bob),
+            Err(StoreLockError::InUse { stale: false, .. })
+        ));
+
+        // Nested writes in the same profile share the lock without releasing it
+        drop(StoreLock::acquire(&store, &alice).unwrap());
+        assert!(StoreLock::acquire(&store, &bob).is_err());
+
+        drop(held);
+        assert_eq!(StoreLock::holder(&store).unwrap(), None);
+        let bobs = StoreLock::acquire(&store, &bob).unwrap();
+        assert_eq!(bobs.owner().profile, bob.display().to_string());
+    }
+
+    #[test]
+    fn test_stale_lock_can_be_taken_over() {
+        let (_temp, store, alice, bob) = accounts();
+        let mut gone = StoreLockOwner::for_profile(&alice);
+        gone.host = "family-imac-old".to_string();
+        gone.heartbeat_at = Utc::now() - Duration::minutes(10);
+        let dir = lock_dir(&store);
+        fs::create_dir_all(&dir).unwrap();
+        write_owner(&dir, &gone).unwrap();
+
+        assert!(matches!(
+            StoreLock::acquire(&store, &bob),
+            Err(StoreLockError::InUse { stale: true, .. })
+        ));
+        let mut taken = StoreLock::take_over(&store, &bob).unwrap();
+        taken.heartbeat().unwrap();
+        let holder = StoreLock::holder(&store).unwrap().unwrap();
+        assert!(holder.same_holder(taken.owner()));
+        assert!(holder.heartbeat_at >= holder.acquired_at);
+
+        // A lock directory without an owner file is judged by its age
+        drop(taken);
+        fs::create_dir_all(&dir).unwrap();
+        assert!(matches!(
+            StoreLock::acquire(&store, &alice),
+            Err(StoreLockError::InUse { stale: false, .. })
+        ));
+    }
+}
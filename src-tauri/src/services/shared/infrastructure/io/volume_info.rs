@@ -0,0 +1,282 @@
+//! Which volume a path is on, and whether it can be unplugged
+//!
+//! USB sticks and SD cards get pulled out as soon as the app says it's done,
+//! so writes to them need a stronger finish than writes to the system disk.
+//! Detection asks the platform: sysfs on Linux (the `removable` flag, or a
+//! device behind a USB bus, since USB SSDs often claim to be fixed) and
+//! `diskutil info` on macOS. Anything that can't be determined is reported as
+//! `VolumeKind::Unknown` and treated like a fixed disk.
+
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tracing::debug;
+
+/// What kind of storage a volume is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, specta::Type)]
+#[serde(rename_all = "snake_case")]
+pub enum VolumeKind {
+    /// Can be unplugged: USB drives, SD cards
+    Removable,
+    /// Internal disk
+    Fixed,
+    Unknown,
+}
+
+/// The mounted volume holding a path
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VolumeInfo {
+    /// Mount point
+    pub root: PathBuf,
+    /// Volume name as shown to the user
+    pub name: String,
+    pub kind: VolumeKind,
+}
+
+impl VolumeInfo {
+    pub fn is_removable(&self) -> bool {
+        self.kind == VolumeKind::Removable
+    }
+}
+
+/// Source of volume information
+pub trait VolumeProbe: Send + Sync {
+    /// Volume holding `path`, or `None` if it can't be determined
+    fn volume_for(&self, path: &Path) -> Option<VolumeInfo>;
+}
+
+/// Asks the operating system
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SystemVolumeProbe;
+
+impl VolumeProbe for SystemVolumeProbe {
+    fn volume_for(&self, path: &Path) -> Option<VolumeInfo> {
+        let path = existing_ancestor(path)?;
+        system_volume_for(&path)
+    }
+}
+
+/// Service answering which volume a path is on
+#[derive(Clone)]
+pub struct VolumeInfoService {
+    probe: Arc<dyn VolumeProbe>,
+}
+
+impl std::fmt::Debug for VolumeInfoService {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("VolumeInfoService").finish_non_exhaustive()
+    }
+}
+
+impl Default for VolumeInfoService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl VolumeInfoService {
+    pub fn new() -> Self {
+        Self::with_probe(SystemVolumeProbe)
+    }
+
+    pub fn with_probe(probe: impl VolumeProbe + 'static) -> Self {
+        Self {
+            probe: Arc::new(probe),
+        }
+    }
+
+    /// Volume holding `path`, if it can be determined
+    pub fn volume_for(&self, path: &Path) -> Option<VolumeInfo> {
+        let volume = self.probe.volume_for(path);
+        debug!(path = %path.display(), volume = ?volume, "Resolved volume");
+        volume
+    }
+
+    /// The removable volume holding `path`; `None` for fixed and unknown
+    /// volumes alike
+    pub fn removable_volume_for(&self, path: &Path) -> Option<VolumeInfo> {
+        self.volume_for(path).filter(VolumeInfo::is_removable)
+    }
+}
+
+/// `path` itself or its closest ancestor that exists, canonicalized
+fn existing_ancestor(path: &Path) -> Option<PathBuf> {
+    path.ancestors()
+        .find(|ancestor| ancestor.exists())
+        .and_then(|ancestor| fs::canonicalize(ancestor).ok())
+}
+
+#[cfg(target_os = "linux")]
+fn system_volume_for(path: &Path) -> Option<VolumeInfo> {
+    use std::os::unix::fs::MetadataExt;
+
+    let mounts = fs::read_to_string("/proc/self/mounts").ok()?;
+    let root = mounts
+        .lines()
+        .filter_map(|line| line.split_whitespace().nth(1))
+        .map(|mount_point| PathBuf::from(mount_point.replace("\\040", " ")))
+        .filter(|mount_point| path.starts_with(mount_point))
+        .max_by_key(|mount_point| mount_point.components().count())?;
+
+    let dev = fs::metadata(path).ok()?.dev();
+    let major = ((dev >> 32) & 0xffff_f000) | ((dev >> 8) & 0x0000_0fff);
+    let minor = ((dev >> 12) & 0xffff_ff00) | (dev & 0x0000_00ff);
+    let device = PathBuf::from(format!("/sys/dev/block/{}:{}", major, minor));
+    // Partitions carry no `removable` flag of their own; the disk's is used
+    let removable_flag = [device.join("removable"), device.join("../removable")]
+        .iter()
+        .find_map(|flag| fs::read_to_string(flag).ok())
+        .map(|value| value.trim() == "1");
+    let sysfs_path = fs::canonicalize(&device).ok();
+
+    Some(VolumeInfo {
+        name: volume_name(&root),
+        kind: linux_volume_kind(removable_flag, sysfs_path.as_deref()),
+        root,
+    })
+}
+
+/// Removable when the kernel says so or the disk hangs off a USB bus;
+/// virtual filesystems have no block device and stay unknown
+#[cfg(any(target_os = "linux", test))]
+fn linux_volume_kind(removable_flag: Option<bool>, sysfs_path: Option<&Path>) -> VolumeKind {
+    let on_usb = sysfs_path.is_some_and(|path| {
+        path.components()
+            .any(|component| component.as_os_str().to_string_lossy().starts_with("usb"))
+    });
+    match (removable_flag, on_usb) {
+        (Some(true), _) | (_, true) => VolumeKind::Removable,
+        (Some(false), false) => VolumeKind::Fixed,
+        (None, false) => VolumeKind::Unknown,
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn system_volume_for(path: &Path) -> Option<VolumeInfo> {
+    let output = std::process::Command::new("diskutil")
+        .arg("info")
+        .arg(path)
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    parse_diskutil_info(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// Volume from `diskutil info` output
+///
+/// External and USB devices count as removable even when their media is
+/// reported as fixed, which is how most USB SSDs describe themselves.
+#[cfg(any(target_os = "macos", test))]
+fn parse_diskutil_info(output: &str) -> Option<VolumeInfo> {
+    let field = |name: &str| {
+        output.lines().find_map(|line| {
+            let (key, value) = line.trim().split_once(':')?;
+            (key.trim() == name).then(|| value.trim().to_string())
+        })
+    };
+
+    let root = PathBuf::from(field("Mount Point").filter(|value| !value.is_empty())?);
+    let name = field("Volume Name")
+        .filter(|value| !value.is_empty())
+        .unwrap_or_else(|| volume_name(&root));
+    let removable_media = field("Removable Media");
+    let kind = if removable_media.as_deref() == Some("Removable")
+        || field("Device Location").as_deref() == Some("External")
+        || field("Protocol").as_deref() == Some("USB")
+    {
+        VolumeKind::Removable
+    } else if removable_media.is_some() {
+        VolumeKind::Fixed
+    } else {
+        VolumeKind::Unknown
+    };
+
+    Some(VolumeInfo { root, name, kind })
+}
+
+/// Volume types need platform APIs here
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+fn system_volume_for(_path: &Path) -> Option<VolumeInfo> {
+    None
+}
+
+#[cfg(any(target_os = "linux", target_os = "macos", test))]
+fn volume_name(root: &Path) -> String {
+    root.file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| root.display().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_linux_volume_kind() {
+        let usb_disk = Path::new(
+            "/sys/devices/pci0000:00/0000:00:14.0/usb2/2-1/2-1:1.0/host6/target6:0:0/6:0:0:0/block/sdb/sdb1",
+        );
+        let sata_disk =
+            Path::new("/sys/devices/pci0000:00/0000:00:17.0/ata1/host0/target0:0:0/block/sda/sda2");
+
+        assert_eq!(linux_volume_kind(Some(true), None), VolumeKind::Removable);
+        // USB SSDs report themselves as fixed
+        assert_eq!(
+            linux_volume_kind(Some(false), Some(usb_disk)),
+            VolumeKind::Removable
+        );
+        assert_eq!(
+            linux_volume_kind(Some(false), Some(sata_disk)),
+            VolumeKind::Fixed
+        );
+        assert_eq!(linux_volume_kind(None, None), VolumeKind::Unknown);
+    }
+
+    #[test]
+    fn test_parse_diskutil_info() {
+        let usb = "   Device Identifier:         disk4s1\n\
+                   Volume Name:               BACKUP STICK\n\
+                   Mount Point:               /Volumes/BACKUP STICK\n\
+                   Protocol:                  USB\n\
+                   Removable Media:           Fixed\n\
+                   Device Location:           External\n";
+        let volume = parse_diskutil_info(usb).unwrap();
+        assert_eq!(volume.root, PathBuf::from("/Volumes/BACKUP STICK"));
+        assert_eq!(volume.name, "BACKUP STICK");
+        assert_eq!(volume.kind, VolumeKind::Removable);
+
+        let internal = "   Volume Name:               Data\n\
+                        Mount Point:               /System/Volumes/Data\n\
+                        Protocol:                  Apple Fabric\n\
+                        Removable Media:           Fixed\n\
+                        Device Location:           Internal\n";
+        assert_eq!(
+            parse_diskutil_info(internal).unwrap().kind,
+            VolumeKind::Fixed
+        );
+
+        assert!(parse_diskutil_info("Could not find disk").is_none());
+    }
+
+    struct NoVolumes;
+
+    impl VolumeProbe for NoVolumes {
+        fn volume_for(&self, _path: &Path) -> Option<VolumeInfo> {
+            None
+        }
+    }
+
+    #[test]
+    fn test_undetectable_volume_is_not_removable() {
+        let service = VolumeInfoService::with_probe(NoVolumes);
+        assert!(service.volume_for(Path::new("/anywhere")).is_none());
+        assert!(
+            service
+                .removable_volume_for(Path::new("/anywhere"))
+                .is_none()
+        );
+    }
+}
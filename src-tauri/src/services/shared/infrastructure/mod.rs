@@ -5,12 +5,18 @@
 
 pub mod binary_resolver;
 pub mod caching;
+pub mod change_events;
+pub mod clock;
+pub mod deep_link;
 pub mod device_identity;
 pub mod error;
+pub mod formatting;
 pub mod io;
 pub mod label_sanitization;
 pub mod path_management;
+pub mod pattern_matching;
 pub mod progress;
+pub mod window_sessions;
 
 // Re-export binary resolver
 pub use binary_resolver::{
@@ -20,28 +26,51 @@
 // Re-export caching
 pub use caching::{CacheMetrics, StorageCache, get_cache};
 
+// Re-export change events
+pub use change_events::{
+    ChangeEvent, ChangeNotification, REGISTRY_CHANGED_EVENT, RegistryRevisions, apply_and_publish,
+    publish, publish_all, registry_revision,
+};
+
+// Re-export clock
+pub use clock::{Clock, ClockReliability, ClockService, ClockStamp, ClockStatus, SystemClock};
+
 // Re-export device identity
 pub use device_identity::DeviceInfo;
 
+// Re-export display formatting
+pub use formatting::{
+    Locale, format_bytes, format_date, format_date_time, format_duration, format_relative,
+};
+
 // Re-export label sanitization
 pub use label_sanitization::{SanitizedLabel, sanitize_label};
 
 // Re-export path management
 pub use path_management::{
-    SanitizedVaultName, generate_backup_timestamp, get_app_dir, get_backups_dir, get_config_dir,
-    get_key_file_path, get_key_metadata_path, get_keys_dir, get_logs_dir, get_manifest_backup_path,
-    get_manifest_backups_dir, get_vault_manifest_path, get_vaults_directory,
-    get_vaults_manifest_dir, sanitize_vault_name,
+    SanitizedVaultName, generate_backup_timestamp, get_app_dir, get_backups_dir, get_cache_dir,
+    get_config_dir, get_key_file_path, get_key_metadata_path, get_keys_dir, get_legacy_app_dirs,
+    get_logs_dir, get_manifest_backup_path, get_manifest_backups_dir, get_vault_manifest_path,
+    get_vaults_directory, get_vaults_manifest_dir, sanitize_vault_name,
 };
 
+// Re-export pattern matching
+pub use pattern_matching::{glob_match, path_matches_any, path_matches_glob};
+
 // Re-export error handling
 pub use error::ErrorHandler;
 
 // Re-export I/O utilities
-pub use io::{SecureTempFile, atomic_write, atomic_write_sync};
+pub use io::{
+    DeleteHonesty, MutationJournal, PendingWrite, RecoveryReport, SecureDeleteService,
+    SecureTempFile, atomic_write, atomic_write_sync,
+};
 
 // Re-export progress tracking
 pub use progress::{
-    ENCRYPTION_IN_PROGRESS, PROGRESS_TRACKER, ProgressManager, get_global_progress,
-    update_global_progress,
+    ENCRYPTION_IN_PROGRESS, OperationGuard, OperationKind, PROGRESS_TRACKER, ProgressManager,
+    begin_exclusive_operation, begin_operation, get_global_progress, update_global_progress,
 };
+
+// Re-export window sessions
+pub use window_sessions::WindowSessions;
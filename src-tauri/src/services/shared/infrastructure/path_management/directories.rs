@@ -9,6 +9,7 @@
 //! - **Linux**: `~/.local/share/com.barqly.vault/` (XDG_DATA_HOME)
 
 use super::provider::PathProvider;
+use super::sensitive_directory::SensitiveDirectory;
 use crate::error::StorageError;
 use std::path::{Path, PathBuf};
 
@@ -57,6 +58,7 @@ pub fn get_keys_dir() -> Result<PathBuf, StorageError> {
 
     let keys_dir = provider.keys_dir()?;
     provider.ensure_dir_exists(&keys_dir)?;
+    SensitiveDirectory::protect(&keys_dir);
     Ok(keys_dir)
 }
 
@@ -82,6 +84,26 @@ pub fn get_logs_dir() -> Result<PathBuf, StorageError> {
     Ok(logs_dir)
 }
 
+/// Get the cache directory
+///
+/// Returns the directory for data that can be rebuilt, kept apart from the
+/// app data directory on Linux (`$XDG_CACHE_HOME`).
+/// The directory is created if it doesn't exist.
+///
+/// # Errors
+/// - `StorageError::DirectoryCreationFailed` if the directory cannot be created
+/// - `StorageError::PermissionDenied` if the directory cannot be accessed
+pub fn get_cache_dir() -> Result<PathBuf, StorageError> {
+    let provider = PathProvider::global()?;
+    let provider = provider
+        .read()
+        .map_err(|_| StorageError::InitializationFailed("PathProvider lock poisoned".into()))?;
+
+    let cache_dir = provider.cache_dir()?;
+    provider.ensure_dir_exists(&cache_dir)?;
+    Ok(cache_dir)
+}
+
 /// Get the config directory
 ///
 /// Returns the directory where configuration files are stored.
@@ -104,6 +126,18 @@ pub fn get_config_dir() -> Result<PathBuf, StorageError> {
     Ok(config_dir)
 }
 
+/// Get the app data directories older builds wrote to
+///
+/// Only existing directories are returned; nothing is created.
+pub fn get_legacy_app_dirs() -> Result<Vec<PathBuf>, StorageError> {
+    let provider = PathProvider::global()?;
+    let provider = provider
+        .read()
+        .map_err(|_| StorageError::InitializationFailed("PathProvider lock poisoned".into()))?;
+
+    Ok(provider.legacy_app_data_dirs())
+}
+
 /// Get the vaults manifest directory (non-sync storage)
 ///
 /// Returns: `~/Library/Application Support/com.barqly.vault/vaults/`
@@ -118,6 +152,46 @@ pub fn get_vaults_manifest_dir() -> Result<PathBuf, StorageError> {
     Ok(vaults_dir)
 }
 
+/// Get the shared vault store, if this profile uses one
+pub fn get_shared_store_dir() -> Result<Option<PathBuf>, StorageError> {
+    let provider = PathProvider::global()?;
+    let provider = provider
+        .read()
+        .map_err(|_| StorageError::InitializationFailed("PathProvider lock poisoned".into()))?;
+    Ok(provider.shared_store_dir().map(Path::to_path_buf))
+}
+
+/// Use `store_dir` as the shared vault store, or stop sharing with `None`
+pub fn set_shared_store_dir(store_dir: Option<&Path>) -> Result<Option<PathBuf>, StorageError> {
+    let provider = PathProvider::global()?;
+    let mut provider = provider
+        .write()
+        .map_err(|_| StorageError::InitializationFailed("PathProvider lock poisoned".into()))?;
+    provider.set_shared_store(store_dir)
+}
+
+/// Every directory holding vault manifests, the shared store's first
+///
+/// The shared store's directory is created without restricting it to this
+/// account, as other accounts read and write it too.
+pub fn get_vault_manifest_dirs() -> Result<Vec<PathBuf>, StorageError> {
+    let provider = PathProvider::global()?;
+    let provider = provider
+        .read()
+        .map_err(|_| StorageError::InitializationFailed("PathProvider lock poisoned".into()))?;
+
+    let mut dirs = Vec::new();
+    if let Some(shared) = provider.shared_manifests_dir() {
+        std::fs::create_dir_all(&shared)
+            .map_err(|_| StorageError::DirectoryCreationFailed(shared.clone()))?;
+        dirs.push(shared);
+    }
+    let own = provider.vaults_manifest_dir()?;
+    provider.ensure_dir_exists(&own)?;
+    dirs.push(own);
+    Ok(dirs)
+}
+
 /// Get the backups directory (non-sync storage)
 ///
 /// Returns: `~/Library/Application Support/com.barqly.vault/backups/`
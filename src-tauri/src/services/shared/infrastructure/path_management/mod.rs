@@ -4,20 +4,38 @@
 //! - **macOS**: `~/Library/Application Support/com.barqly.vault/`
 //! - **Windows**: `%APPDATA%\com.barqly.vault\`
 //! - **Linux**: `~/.local/share/com.barqly.vault/` (XDG_DATA_HOME)
+//!
+//! Linux honors the XDG base directory variables, and portable mode keeps
+//! everything beside the executable (see `storage_mode`).
 
 mod directories;
 mod key_paths;
 mod provider;
+mod sensitive_directory;
+mod shared_store;
+mod storage_mode;
 mod user_vaults;
 mod validation;
 
 // Re-export all public functions to maintain API compatibility
 pub use directories::{
-    get_app_dir, get_backups_dir, get_config_dir, get_keys_dir, get_logs_dir,
-    get_manifest_backups_dir, get_vaults_manifest_dir,
+    get_app_dir, get_backups_dir, get_cache_dir, get_config_dir, get_keys_dir, get_legacy_app_dirs,
+    get_logs_dir, get_manifest_backups_dir, get_shared_store_dir, get_vault_manifest_dirs,
+    get_vaults_manifest_dir, set_shared_store_dir,
 };
 pub use key_paths::{get_key_file_path, get_key_metadata_path};
-pub use provider::{PathProvider, init_path_provider, update_with_app_handle};
+pub use provider::{
+    PathProvider, StorageLocationReport, StoragePaths, get_profile_warning, get_storage_paths,
+    init_path_provider, update_with_app_handle,
+};
+pub use sensitive_directory::{IndexingProtection, NEVER_INDEX_MARKER, SensitiveDirectory};
+pub use shared_store::{
+    SHARED_STORE_FILENAME, STORE_LOCKS_DIR, STORE_MANIFESTS_DIR, SharedStoreSetting,
+    validate_store_location,
+};
+pub use storage_mode::{
+    PORTABLE_DATA_DIR, PORTABLE_ENV_VAR, PORTABLE_MARKER_FILE, StorageLayout, StorageMode,
+};
 pub use user_vaults::{
     SanitizedVaultName, generate_backup_timestamp, get_manifest_backup_path,
     get_recovery_directory, get_vault_file_path, get_vault_manifest_path, get_vault_recovery_path,
@@ -14,10 +14,22 @@
 //! ### User Documents (sync-friendly):
 //! - `~/Documents/Barqly-Vaults/` - Encrypted .age files
 //! - `~/Documents/Barqly-Recovery/` - Decrypted files
-
+//!
+//! On Linux, config, logs, and cache follow `XDG_CONFIG_HOME`, `XDG_STATE_HOME`,
+//! and `XDG_CACHE_HOME`. Portable mode moves everything beside the executable;
+//! see `storage_mode`.
+
+use super::shared_store::{
+    SHARED_STORE_FILENAME, STORE_MANIFESTS_DIR, SharedStoreSetting, validate_store_location,
+};
+use super::storage_mode::{StorageLayout, StorageMode, detect_profile_conflict};
 use crate::error::StorageError;
+use crate::services::shared::infrastructure::io::{
+    CloudSyncProvider, FsCapabilities, capabilities_for, detect_cloud_sync,
+};
 use directories::{BaseDirs, ProjectDirs, UserDirs};
 use once_cell::sync::OnceCell;
+use serde::Serialize;
 use std::path::{Path, PathBuf};
 use std::sync::RwLock;
 use tauri::{AppHandle, Manager};
@@ -64,6 +76,63 @@ pub struct PathProvider {
     platform: Platform,
     /// Whether we're in headless mode (CI/Docker)
     headless_mode: bool,
+    /// Portable / XDG overrides resolved at startup
+    layout: StorageLayout,
+    /// Set when portable mode ignores an existing default-location profile
+    profile_warning: Option<String>,
+    /// Vault store shared with other accounts, see `shared_store`
+    shared_store: Option<PathBuf>,
+}
+
+/// Resolved storage locations, for display in settings
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct StoragePaths {
+    pub mode: StorageMode,
+    pub data_dir: String,
+    pub config_dir: String,
+    pub keys_dir: String,
+    pub vaults_manifest_dir: String,
+    pub logs_dir: String,
+    pub cache_dir: String,
+    pub user_vaults_dir: String,
+    pub user_recovery_dir: String,
+    /// Vault store shared with other OS accounts, if one is set
+    pub shared_store_dir: Option<String>,
+    /// Set when an existing profile elsewhere is being ignored
+    pub profile_warning: Option<String>,
+    /// Write guarantees of the locations Barqly Vault saves to
+    pub storage_locations: Vec<StorageLocationReport>,
+}
+
+/// Filesystem capabilities of one storage location
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct StorageLocationReport {
+    /// Which location this is, e.g. "Vaults"
+    pub label: String,
+    pub path: String,
+    pub capabilities: FsCapabilities,
+    /// Set when a sync client manages the location
+    pub cloud_sync: Option<CloudSyncProvider>,
+    /// User-facing warnings about missing guarantees
+    pub warnings: Vec<String>,
+}
+
+impl StorageLocationReport {
+    /// Probe `path` (cached per mount) and collect warnings
+    pub fn for_location(label: &str, path: &Path) -> Self {
+        let capabilities = capabilities_for(path);
+        let cloud_sync = detect_cloud_sync(path);
+        let mut warnings = capabilities.warnings();
+        warnings.extend(cloud_sync.map(CloudSyncProvider::warning));
+
+        Self {
+            label: label.to_string(),
+            path: path.display().to_string(),
+            capabilities,
+            cloud_sync,
+            warnings,
+        }
+    }
 }
 
 impl PathProvider {
@@ -76,11 +145,24 @@ pub fn initialize() -> Result<(), StorageError> {
             return Ok(()); // Already initialized
         }
 
-        let provider = PathProvider {
+        let platform = Platform::current();
+        let layout = StorageLayout::detect(platform);
+        let profile_warning = match (&layout.mode, &layout.data_dir) {
+            (StorageMode::Portable, Some(portable_root)) => Self::default_app_data_dir(platform)
+                .ok()
+                .and_then(|default_dir| detect_profile_conflict(portable_root, &default_dir)),
+            _ => None,
+        };
+
+        let mut provider = PathProvider {
             app_handle: None,
-            platform: Platform::current(),
+            platform,
             headless_mode: Self::detect_headless_mode(),
+            layout,
+            profile_warning,
+            shared_store: None,
         };
+        provider.shared_store = provider.load_shared_store();
 
         PATH_PROVIDER.set(RwLock::new(provider)).map_err(|_| {
             StorageError::InitializationFailed("PathProvider already initialized".into())
@@ -119,6 +201,21 @@ pub fn global() -> Result<&'static RwLock<PathProvider>, StorageError> {
         })
     }
 
+    /// A provider rooted at `root`, separate from the global one
+    ///
+    /// Every location resolves under `root` as in portable mode. Used to
+    /// rehearse operations on a pristine profile without touching the live one.
+    pub fn sandboxed(root: PathBuf) -> Self {
+        PathProvider {
+            app_handle: None,
+            platform: Platform::current(),
+            headless_mode: false,
+            layout: StorageLayout::portable(root),
+            profile_warning: None,
+            shared_store: None,
+        }
+    }
+
     /// Detect if we're running in a headless environment
     fn detect_headless_mode() -> bool {
         // Check common CI/Docker environment variables
@@ -140,7 +237,12 @@ fn detect_headless_mode() -> bool {
     /// - **macOS**: `~/Library/Application Support/com.barqly.vault/`
     /// - **Windows**: `%APPDATA%\com.barqly.vault\`
     /// - **Linux**: `~/.local/share/com.barqly.vault/` (XDG_DATA_HOME)
+    /// - **Portable**: `./barqly-data/` beside the executable
     pub fn app_config_dir(&self) -> Result<PathBuf, StorageError> {
+        if let Some(ref dir) = self.layout.data_dir {
+            return Ok(dir.clone());
+        }
+
         // Try Tauri path resolver first if available
         if let Some(ref app_handle) = self.app_handle
             && let Ok(path) = app_handle.path().app_data_dir()
@@ -148,10 +250,15 @@ pub fn app_config_dir(&self) -> Result<PathBuf, StorageError> {
             return Ok(path);
         }
         // Fall through to manual construction if Tauri fails
+        Self::default_app_data_dir(self.platform)
+    }
 
-        // Manual construction for bootstrap and fallback
-        // CRITICAL: Must match Tauri's naming exactly!
-        match self.platform {
+    /// Platform default app data directory, ignoring any overrides
+    ///
+    /// Manual construction for bootstrap and fallback.
+    /// CRITICAL: Must match Tauri's naming exactly!
+    fn default_app_data_dir(platform: Platform) -> Result<PathBuf, StorageError> {
+        match platform {
             Platform::MacOS => {
                 // macOS: ~/Library/Application Support/com.barqly.vault/
                 if let Some(proj_dirs) = ProjectDirs::from("com", "barqly", "vault") {
@@ -193,6 +300,43 @@ pub fn app_config_dir(&self) -> Result<PathBuf, StorageError> {
         }
     }
 
+    /// App data directories written by older builds
+    ///
+    /// Early releases let `ProjectDirs` pick the directory name, giving
+    /// `barqly\vault` on Windows, plain `vault` on Linux and
+    /// `com.Barqly.Vault` on macOS. Only existing directories other than the
+    /// current one are returned, and none in portable mode.
+    pub fn legacy_app_data_dirs(&self) -> Vec<PathBuf> {
+        if self.layout.mode == StorageMode::Portable {
+            return Vec::new();
+        }
+        let Some(base_dirs) = BaseDirs::new() else {
+            return Vec::new();
+        };
+
+        let candidates = match self.platform {
+            Platform::MacOS => vec![base_dirs.data_dir().join("com.Barqly.Vault")],
+            Platform::Windows => {
+                let old_root = base_dirs.config_dir().join("barqly").join("vault");
+                vec![old_root.join("data"), old_root.join("config")]
+            }
+            Platform::Linux => vec![
+                base_dirs.data_dir().join("vault"),
+                base_dirs.config_dir().join("vault"),
+            ],
+        };
+
+        let current = self
+            .app_config_dir()
+            .ok()
+            .and_then(|dir| dir.canonicalize().ok());
+        candidates
+            .into_iter()
+            .filter(|dir| dir.is_dir())
+            .filter(|dir| current.is_none() || dir.canonicalize().ok() != current)
+            .collect()
+    }
+
     /// Get the user's Documents directory with headless fallback
     ///
     /// Returns the Documents directory, or a fallback location in headless environments.
@@ -202,6 +346,10 @@ pub fn app_config_dir(&self) -> Result<PathBuf, StorageError> {
     /// 2. In headless mode: use ~/.local/share/barqly-documents/
     /// 3. Last resort: use home directory
     pub fn documents_dir(&self) -> Result<PathBuf, StorageError> {
+        if let Some(ref dir) = self.layout.documents_dir {
+            return Ok(dir.clone());
+        }
+
         // Try standard Documents directory first
         if let Some(user_dirs) = UserDirs::new()
             && let Some(doc_dir) = user_dirs.document_dir()
@@ -237,9 +385,20 @@ pub fn keys_dir(&self) -> Result<PathBuf, StorageError> {
         Ok(self.app_config_dir()?.join("keys"))
     }
 
-    /// Get the logs subdirectory
+    /// Get the logs directory (`$XDG_STATE_HOME/com.barqly.vault/logs` on Linux)
     pub fn logs_dir(&self) -> Result<PathBuf, StorageError> {
-        Ok(self.app_config_dir()?.join("logs"))
+        match self.layout.logs_dir {
+            Some(ref dir) => Ok(dir.clone()),
+            None => Ok(self.app_config_dir()?.join("logs")),
+        }
+    }
+
+    /// Get the cache directory (`$XDG_CACHE_HOME/com.barqly.vault` on Linux)
+    pub fn cache_dir(&self) -> Result<PathBuf, StorageError> {
+        match self.layout.cache_dir {
+            Some(ref dir) => Ok(dir.clone()),
+            None => Ok(self.app_config_dir()?.join("cache")),
+        }
     }
 
     /// Get the vaults manifest subdirectory (non-sync storage)
@@ -247,9 +406,24 @@ pub fn vaults_manifest_dir(&self) -> Result<PathBuf, StorageError> {
         Ok(self.app_config_dir()?.join("vaults"))
     }
 
-    /// Get the config subdirectory
+    /// Get the config directory (`$XDG_CONFIG_HOME/com.barqly.vault` on Linux)
+    ///
+    /// Settings written by earlier releases under the data directory keep
+    /// being used until the XDG location exists, so upgrading doesn't
+    /// silently reset them.
     pub fn config_dir(&self) -> Result<PathBuf, StorageError> {
-        Ok(self.app_config_dir()?.join("config"))
+        let legacy = self.app_config_dir()?.join("config");
+        match self.layout.config_dir {
+            Some(ref dir)
+                if self.layout.mode == StorageMode::Standard
+                    && !dir.exists()
+                    && legacy.exists() =>
+            {
+                Ok(legacy)
+            }
+            Some(ref dir) => Ok(dir.clone()),
+            None => Ok(legacy),
+        }
     }
 
     /// Get the backups subdirectory
@@ -262,6 +436,57 @@ pub fn manifest_backups_dir(&self) -> Result<PathBuf, StorageError> {
         Ok(self.backups_dir()?.join("manifest"))
     }
 
+    /// Vault store shared with other accounts, if this profile uses one
+    pub fn shared_store_dir(&self) -> Option<&Path> {
+        self.shared_store.as_deref()
+    }
+
+    /// Manifests directory of the shared store
+    pub fn shared_manifests_dir(&self) -> Option<PathBuf> {
+        self.shared_store
+            .as_ref()
+            .map(|store| store.join(STORE_MANIFESTS_DIR))
+    }
+
+    /// Use `store_dir` as this profile's shared store, or stop sharing
+    ///
+    /// Vaults already in a store stay there; they're listed again once the
+    /// store is set back.
+    pub fn set_shared_store(
+        &mut self,
+        store_dir: Option<&Path>,
+    ) -> Result<Option<PathBuf>, StorageError> {
+        let store_dir = store_dir
+            .map(|dir| validate_store_location(dir, &self.app_config_dir()?))
+            .transpose()?;
+        let config_dir = self.config_dir()?;
+        self.ensure_dir_exists(&config_dir)?;
+        SharedStoreSetting {
+            store_dir: store_dir.clone(),
+            ..Default::default()
+        }
+        .save_to(&config_dir.join(SHARED_STORE_FILENAME))?;
+        self.shared_store = store_dir.clone();
+        Ok(store_dir)
+    }
+
+    /// The shared store recorded in the profile, ignoring one that's gone
+    fn load_shared_store(&self) -> Option<PathBuf> {
+        let path = self.config_dir().ok()?.join(SHARED_STORE_FILENAME);
+        let store_dir = match SharedStoreSetting::load_from(&path) {
+            Ok(setting) => setting.store_dir?,
+            Err(e) => {
+                warn!(error = %e, "Failed to read shared store setting");
+                return None;
+            }
+        };
+        if !store_dir.is_dir() {
+            warn!(store = %store_dir.display(), "Shared store not found, using this profile only");
+            return None;
+        }
+        Some(store_dir)
+    }
+
     /// Get the user-visible Barqly-Vaults directory
     pub fn user_vaults_dir(&self) -> Result<PathBuf, StorageError> {
         Ok(self.documents_dir()?.join("Barqly-Vaults"))
@@ -282,6 +507,43 @@ pub fn key_registry_path(&self) -> Result<PathBuf, StorageError> {
         Ok(self.keys_dir()?.join("barqly-vault-key-registry.json"))
     }
 
+    /// Active storage mode
+    pub fn storage_mode(&self) -> StorageMode {
+        self.layout.mode
+    }
+
+    /// Warning about an existing profile that portable mode is ignoring
+    pub fn profile_warning(&self) -> Option<&str> {
+        self.profile_warning.as_deref()
+    }
+
+    /// All resolved storage locations and the active mode
+    pub fn storage_paths(&self) -> Result<StoragePaths, StorageError> {
+        let display = |path: PathBuf| path.display().to_string();
+        let mut storage_locations = vec![
+            StorageLocationReport::for_location("App data", &self.app_config_dir()?),
+            StorageLocationReport::for_location("Keys", &self.keys_dir()?),
+            StorageLocationReport::for_location("Vaults", &self.user_vaults_dir()?),
+        ];
+        if let Some(store) = &self.shared_store {
+            storage_locations.push(StorageLocationReport::for_location("Shared store", store));
+        }
+        Ok(StoragePaths {
+            mode: self.layout.mode,
+            data_dir: display(self.app_config_dir()?),
+            config_dir: display(self.config_dir()?),
+            keys_dir: display(self.keys_dir()?),
+            vaults_manifest_dir: display(self.vaults_manifest_dir()?),
+            logs_dir: display(self.logs_dir()?),
+            cache_dir: display(self.cache_dir()?),
+            user_vaults_dir: display(self.user_vaults_dir()?),
+            user_recovery_dir: display(self.user_recovery_dir()?),
+            shared_store_dir: self.shared_store.clone().map(display),
+            profile_warning: self.profile_warning.clone(),
+            storage_locations,
+        })
+    }
+
     /// Ensure a directory exists with proper permissions
     ///
     /// Creates the directory if it doesn't exist and sets appropriate permissions.
@@ -336,6 +598,22 @@ pub fn update_with_app_handle(app_handle: AppHandle) -> Result<(), StorageError>
     PathProvider::set_app_handle(app_handle)
 }
 
+/// Resolved storage locations and the active mode
+pub fn get_storage_paths() -> Result<StoragePaths, StorageError> {
+    let provider = PathProvider::global()?;
+    let provider = provider
+        .read()
+        .map_err(|_| StorageError::InitializationFailed("PathProvider lock poisoned".into()))?;
+    provider.storage_paths()
+}
+
+/// Warning about an existing profile that portable mode is ignoring
+pub fn get_profile_warning() -> Option<String> {
+    let provider = PathProvider::global().ok()?;
+    let provider = provider.read().ok()?;
+    provider.profile_warning().map(str::to_string)
+}
+
 /// Get the application configuration directory
 #[allow(dead_code)] // For future use
 pub fn get_app_config_dir_compat() -> Result<PathBuf, StorageError> {
@@ -397,6 +675,24 @@ fn test_path_provider_initialization() {
         assert!(PathProvider::global().is_ok());
     }
 
+    #[test]
+    fn test_sandboxed_provider_stays_under_root() {
+        let temp = TempDir::new().unwrap();
+        let provider = PathProvider::sandboxed(temp.path().to_path_buf());
+
+        for path in [
+            provider.keys_dir().unwrap(),
+            provider.vaults_manifest_dir().unwrap(),
+            provider.config_dir().unwrap(),
+            provider.user_vaults_dir().unwrap(),
+            provider.user_recovery_dir().unwrap(),
+            provider.key_registry_path().unwrap(),
+        ] {
+            assert!(path.starts_with(temp.path()), "{}", path.display());
+        }
+        assert_eq!(provider.shared_store_dir(), None);
+    }
+
     #[test]
     fn test_platform_detection() {
         let platform = Platform::current();
@@ -438,7 +734,11 @@ fn test_subdirectory_paths() {
         let logs_dir = provider.logs_dir().unwrap();
 
         assert_eq!(keys_dir, app_dir.join("keys"));
-        assert_eq!(logs_dir, app_dir.join("logs"));
+        // Logs move to XDG_STATE_HOME on Linux
+        match provider.layout.logs_dir {
+            Some(ref dir) => assert_eq!(logs_dir, *dir),
+            None => assert_eq!(logs_dir, app_dir.join("logs")),
+        }
     }
 
     #[test]
@@ -477,6 +777,95 @@ fn test_ensure_dir_exists() {
         assert!(test_path.is_dir());
     }
 
+    #[test]
+    fn test_overrides_replace_platform_dirs() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path().join("barqly-data");
+        let provider = PathProvider {
+            app_handle: None,
+            platform: Platform::current(),
+            headless_mode: false,
+            layout: StorageLayout::portable(root.clone()),
+            profile_warning: None,
+            shared_store: None,
+        };
+
+        assert_eq!(provider.app_config_dir().unwrap(), root);
+        assert_eq!(provider.keys_dir().unwrap(), root.join("keys"));
+        assert_eq!(provider.config_dir().unwrap(), root.join("config"));
+        assert_eq!(provider.logs_dir().unwrap(), root.join("logs"));
+        assert_eq!(
+            provider.user_vaults_dir().unwrap(),
+            root.join("Barqly-Vaults")
+        );
+
+        let paths = provider.storage_paths().unwrap();
+        assert_eq!(paths.mode, StorageMode::Portable);
+        assert_eq!(paths.cache_dir, root.join("cache").display().to_string());
+    }
+
+    #[test]
+    fn test_config_dir_keeps_legacy_location() {
+        let temp_dir = TempDir::new().unwrap();
+        let data_dir = temp_dir.path().join("data");
+        let xdg_config = temp_dir.path().join("xdg-config");
+        let provider = PathProvider {
+            app_handle: None,
+            platform: Platform::Linux,
+            headless_mode: false,
+            layout: StorageLayout {
+                data_dir: Some(data_dir.clone()),
+                config_dir: Some(xdg_config.clone()),
+                ..StorageLayout::standard()
+            },
+            profile_warning: None,
+            shared_store: None,
+        };
+
+        // Fresh install: XDG location
+        assert_eq!(provider.config_dir().unwrap(), xdg_config);
+
+        // Upgrade: settings from an earlier release stay in use
+        std::fs::create_dir_all(data_dir.join("config")).unwrap();
+        assert_eq!(provider.config_dir().unwrap(), data_dir.join("config"));
+
+        std::fs::create_dir_all(&xdg_config).unwrap();
+        assert_eq!(provider.config_dir().unwrap(), xdg_config);
+    }
+
+    #[test]
+    fn test_profiles_share_a_store_but_keep_their_config() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = temp_dir.path().join("Shared");
+        std::fs::create_dir_all(&store).unwrap();
+        let profile = |user: &str| PathProvider {
+            app_handle: None,
+            platform: Platform::current(),
+            headless_mode: false,
+            layout: StorageLayout::portable(temp_dir.path().join(user)),
+            profile_warning: None,
+            shared_store: None,
+        };
+        let mut alice = profile("alice");
+        let mut bob = profile("bob");
+
+        alice.set_shared_store(Some(&store)).unwrap();
+        bob.set_shared_store(Some(&store)).unwrap();
+        assert_eq!(alice.shared_manifests_dir(), bob.shared_manifests_dir());
+        assert_ne!(alice.config_dir().unwrap(), bob.config_dir().unwrap());
+        assert_eq!(alice.load_shared_store(), alice.shared_store);
+
+        // A store inside the profile isn't shared
+        let own = alice.app_config_dir().unwrap().join("store");
+        std::fs::create_dir_all(&own).unwrap();
+        assert!(alice.set_shared_store(Some(&own)).is_err());
+
+        bob.set_shared_store(None).unwrap();
+        assert_eq!(bob.load_shared_store(), None);
+        assert!(bob.storage_paths().unwrap().shared_store_dir.is_none());
+        assert!(alice.storage_paths().unwrap().shared_store_dir.is_some());
+    }
+
     #[cfg(unix)]
     #[test]
     fn test_unix_permissions() {
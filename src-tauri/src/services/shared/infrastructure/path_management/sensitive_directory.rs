@@ -0,0 +1,271 @@
+//! Directories kept out of the OS search index
+//!
+//! Spotlight indexes file names and content snippets, and its index keeps
+//! them after the files are securely deleted. Directories that hold key
+//! files or plaintext (the keys directory, staging, quarantine and
+//! decrypted output with a cleanup session) are created through
+//! `SensitiveDirectory`, which asks indexers and backups to leave them
+//! alone:
+//!
+//! - Everywhere: a `.metadata_never_index` marker in the directory
+//! - macOS: the Time Machine exclusion attribute, so plaintext doesn't
+//!   reach a backup either
+//! - Linux: a `.trackerignore` marker for GNOME Tracker. The app's data
+//!   lives under hidden directories (`~/.local/share`, `~/.cache`), which
+//!   Tracker and KDE Baloo skip by default, but nothing can stop an indexer
+//!   configured to include them, and decrypted output goes where the user
+//!   chooses
+//!
+//! Applying a protection is best effort: a directory is still created when
+//! a marker or attribute can't be written, and `SensitiveDirectory::status`
+//! reports what's in place.
+
+use serde::Serialize;
+use std::ffi::OsStr;
+use std::fs;
+use std::io;
+use std::path::{Component, Path};
+use tracing::{debug, warn};
+
+/// Marker asking Spotlight not to index a directory
+pub const NEVER_INDEX_MARKER: &str = ".metadata_never_index";
+
+/// Marker asking GNOME Tracker not to index a directory
+#[cfg(target_os = "linux")]
+const TRACKER_IGNORE_MARKER: &str = ".trackerignore";
+
+/// Marker files this module writes
+#[cfg(target_os = "linux")]
+const MARKERS: &[&str] = &[NEVER_INDEX_MARKER, TRACKER_IGNORE_MARKER];
+#[cfg(not(target_os = "linux"))]
+const MARKERS: &[&str] = &[NEVER_INDEX_MARKER];
+
+/// Extended attribute excluding an item from Time Machine
+#[cfg(target_os = "macos")]
+const BACKUP_EXCLUDE_ATTR: &str = "com.apple.metadata:com_apple_backup_excludeItem";
+
+/// `com.apple.backupd` as a binary property list, the value `tmutil` writes
+#[cfg(target_os = "macos")]
+const BACKUP_EXCLUDE_VALUE: &[u8] = b"bplist00_\x10\x11com.apple.backupd\x08\0\0\0\0\0\0\x01\x01\0\0\0\0\0\0\0\x01\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\x1c";
+
+/// Which protections a directory has
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, specta::Type)]
+pub struct IndexingProtection {
+    /// The never-index marker(s) are in the directory
+    pub marker: bool,
+    /// Excluded from Time Machine; `None` off macOS
+    pub backup_excluded: Option<bool>,
+    /// Below a hidden directory that desktop indexers skip by default;
+    /// `None` off Linux
+    pub hidden_location: Option<bool>,
+}
+
+impl IndexingProtection {
+    /// Every protection this platform offers is in place
+    pub fn is_complete(&self) -> bool {
+        self.marker && self.backup_excluded != Some(false)
+    }
+}
+
+/// Creates and protects directories holding keys or plaintext
+pub struct SensitiveDirectory;
+
+impl SensitiveDirectory {
+    /// Create `path` and its parents, then protect it
+    ///
+    /// Fails only if the directory can't be created; missing protections
+    /// are logged.
+    pub fn create(path: &Path) -> io::Result<IndexingProtection> {
+        fs::create_dir_all(path)?;
+        Ok(Self::protect(path))
+    }
+
+    /// Protect the existing directory `path`, skipping what's in place
+    pub fn protect(path: &Path) -> IndexingProtection {
+        for marker in MARKERS {
+            let marker_path = path.join(marker);
+            if !marker_path.exists()
+                && let Err(e) = fs::write(&marker_path, b"")
+            {
+                warn!(path = %path.display(), marker, error = %e, "Failed to write indexing marker");
+            }
+        }
+
+        #[cfg(target_os = "macos")]
+        if !macos::has_attr(path, BACKUP_EXCLUDE_ATTR)
+            && let Err(e) = macos::set_attr(path, BACKUP_EXCLUDE_ATTR, BACKUP_EXCLUDE_VALUE)
+        {
+            warn!(path = %path.display(), error = %e, "Failed to exclude directory from backups");
+        }
+
+        let protection = Self::status(path);
+        debug!(path = %path.display(), ?protection, "Protected sensitive directory");
+        protection
+    }
+
+    /// Protections in place on `path`, without changing anything
+    pub fn status(path: &Path) -> IndexingProtection {
+        IndexingProtection {
+            marker: MARKERS.iter().all(|marker| path.join(marker).is_file()),
+            #[cfg(target_os = "macos")]
+            backup_excluded: Some(macos::has_attr(path, BACKUP_EXCLUDE_ATTR)),
+            #[cfg(not(target_os = "macos"))]
+            backup_excluded: None,
+            #[cfg(target_os = "linux")]
+            hidden_location: Some(is_hidden_location(path)),
+            #[cfg(not(target_os = "linux"))]
+            hidden_location: None,
+        }
+    }
+
+    /// `name` is a marker written by this module, not user data
+    pub fn is_marker(name: &OsStr) -> bool {
+        MARKERS.iter().any(|marker| name == OsStr::new(marker))
+    }
+}
+
+/// `path` has a dot-directory among its components
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn is_hidden_location(path: &Path) -> bool {
+    path.components().any(|component| match component {
+        Component::Normal(name) => name.to_string_lossy().starts_with('.'),
+        _ => false,
+    })
+}
+
+#[cfg(target_os = "macos")]
+mod macos {
+    use nix::libc;
+    use std::ffi::CString;
+    use std::io;
+    use std::os::unix::ffi::OsStrExt;
+    use std::path::Path;
+
+    fn c_path(path: &Path) -> io::Result<CString> {
+        CString::new(path.as_os_str().as_bytes())
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))
+    }
+
+    pub fn has_attr(path: &Path, name: &str) -> bool {
+        let (Ok(path), Ok(name)) = (c_path(path), CString::new(name)) else {
+            return false;
+        };
+        // SAFETY: both strings are NUL-terminated; a null buffer asks for
+        // the value's size only
+        let size =
+            unsafe { libc::getxattr(path.as_ptr(), name.as_ptr(), std::ptr::null_mut(), 0, 0, 0) };
+        size >= 0
+    }
+
+    pub fn set_attr(path: &Path, name: &str, value: &[u8]) -> io::Result<()> {
+        let path = c_path(path)?;
+        let name =
+            CString::new(name).map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+        // SAFETY: both strings are NUL-terminated and `value` outlives the call
+        let result = unsafe {
+            libc::setxattr(
+                path.as_ptr(),
+                name.as_ptr(),
+                value.as_ptr().cast(),
+                value.len(),
+                0,
+                0,
+            )
+        };
+        if result == 0 {
+            Ok(())
+        } else {
+            Err(io::Error::last_os_error())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_created_directory_carries_markers() {
+        let temp = TempDir::new().unwrap();
+        let dir = temp.path().join("keys");
+
+        let protection = SensitiveDirectory::create(&dir).unwrap();
+        assert!(dir.join(NEVER_INDEX_MARKER).is_file());
+        assert!(protection.marker);
+        assert!(protection.is_complete());
+        assert!(SensitiveDirectory::is_marker(OsStr::new(
+            NEVER_INDEX_MARKER
+        )));
+        assert!(!SensitiveDirectory::is_marker(OsStr::new("notes.txt")));
+
+        // Protecting again leaves things as they are
+        assert_eq!(SensitiveDirectory::protect(&dir), protection);
+        let unprotected = temp.path().join("plain");
+        fs::create_dir(&unprotected).unwrap();
+        assert!(!SensitiveDirectory::status(&unprotected).marker);
+    }
+
+    #[cfg(target_os = "macos")]
+    #[test]
+    fn test_created_directory_is_excluded_from_backups() {
+        let temp = TempDir::new().unwrap();
+        let dir = temp.path().join("staging");
+
+        let protection = SensitiveDirectory::create(&dir).unwrap();
+        assert_eq!(protection.backup_excluded, Some(true));
+        assert!(macos::has_attr(&dir, BACKUP_EXCLUDE_ATTR));
+    }
+
+    #[test]
+    fn test_hidden_location() {
+        assert!(is_hidden_location(Path::new(
+            "/home/u/.local/share/com.barqly.vault/keys"
+        )));
+        assert!(!is_hidden_location(Path::new("/home/u/Documents/Barqly")));
+    }
+
+    /// Sources creating the keys directory and staging areas
+    const SENSITIVE_SOURCES: &[(&str, &str)] = &[
+        ("directories.rs", include_str!("directories.rs")),
+        (
+            "staging.rs",
+            include_str!("../../../file/infrastructure/file_operations/staging.rs"),
+        ),
+    ];
+
+    /// Body of `fn name(` up to the closing brace at its indentation
+    fn fn_body<'a>(source: &'a str, name: &str) -> &'a str {
+        let start = source
+            .find(&format!("fn {name}("))
+            .unwrap_or_else(|| panic!("no fn {name}"));
+        let body = &source[start..];
+        let end = body
+            .find("\n}\n")
+            .into_iter()
+            .chain(body.find("\n    }\n"))
+            .min()
+            .unwrap_or(body.len());
+        &body[..end]
+    }
+
+    #[test]
+    fn test_keys_and_staging_use_sensitive_directory() {
+        for (file, function) in [("directories.rs", "get_keys_dir"), ("staging.rs", "new")] {
+            let source = SENSITIVE_SOURCES
+                .iter()
+                .find(|(name, _)| *name == file)
+                .unwrap()
+                .1;
+            let body = fn_body(source, function);
+            assert!(
+                body.contains("SensitiveDirectory::"),
+                "{file}: {function} must create its directory through SensitiveDirectory"
+            );
+            assert!(
+                !body.contains("create_dir_all"),
+                "{file}: {function} creates a directory without protecting it"
+            );
+        }
+    }
+}
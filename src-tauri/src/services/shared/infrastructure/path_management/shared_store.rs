@@ -0,0 +1,150 @@
+//! Shared vault store
+//!
+//! Several OS accounts on one machine can keep their vaults in one store on
+//! a shared volume, e.g. `/Users/Shared/Barqly`. The store holds what the
+//! accounts share: vault manifests, the archive index of those vaults and the
+//! store lock. Everything else stays in each account's profile, so the
+//! current vault, favorites and vault order, notification snoozes and the
+//! passphrase cache remain per user.
+//!
+//! A profile records the store it uses in `shared_store.json` in its config
+//! directory. A store inside the profile's own data directory isn't shared
+//! and is refused.
+
+use crate::error::StorageError;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tracing::debug;
+
+pub const SHARED_STORE_FILENAME: &str = "shared_store.json";
+const SHARED_STORE_SCHEMA: &str = "barqly.vault.shared-store/1";
+
+/// Vault manifests inside the store
+pub const STORE_MANIFESTS_DIR: &str = "vaults";
+/// Store lock directories inside the store
+pub const STORE_LOCKS_DIR: &str = "locks";
+
+/// The shared store a profile uses, if any
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SharedStoreSetting {
+    pub schema: String,
+    #[serde(default)]
+    pub store_dir: Option<PathBuf>,
+}
+
+impl Default for SharedStoreSetting {
+    fn default() -> Self {
+        Self {
+            schema: SHARED_STORE_SCHEMA.to_string(),
+            store_dir: None,
+        }
+    }
+}
+
+impl SharedStoreSetting {
+    pub fn load_from(path: &Path) -> Result<Self, StorageError> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = fs::read_to_string(path).map_err(|source| StorageError::FileReadFailed {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        Ok(serde_json::from_str(&content)?)
+    }
+
+    pub fn save_to(&self, path: &Path) -> Result<(), StorageError> {
+        let json = serde_json::to_string_pretty(self)?;
+        fs::write(path, json).map_err(|source| StorageError::FileWriteFailed {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        debug!(
+            shared = self.store_dir.is_some(),
+            "Saved shared store setting"
+        );
+        Ok(())
+    }
+}
+
+/// Check `store_dir` can serve as a shared store for the profile whose data
+/// directory is `app_data_dir`
+///
+/// The folder must exist, be outside the profile and be writable by this
+/// account; creating it with access for the other accounts is left to the
+/// user, as only they know who should have it.
+pub fn validate_store_location(
+    store_dir: &Path,
+    app_data_dir: &Path,
+) -> Result<PathBuf, StorageError> {
+    if !store_dir.is_absolute() {
+        return Err(StorageError::InvalidMetadata(format!(
+            "The shared store must be an absolute path, not '{}'",
+            store_dir.display()
+        )));
+    }
+    let store_dir = store_dir
+        .canonicalize()
+        .map_err(|source| StorageError::FileReadFailed {
+            path: store_dir.to_path_buf(),
+            source,
+        })?;
+    if !store_dir.is_dir() {
+        return Err(StorageError::InvalidMetadata(format!(
+            "'{}' isn't a folder",
+            store_dir.display()
+        )));
+    }
+    let profile = app_data_dir
+        .canonicalize()
+        .unwrap_or_else(|_| app_data_dir.to_path_buf());
+    if store_dir.starts_with(&profile) || profile.starts_with(&store_dir) {
+        return Err(StorageError::InvalidMetadata(format!(
+            "'{}' overlaps this account's own app data, so other accounts can't share it",
+            store_dir.display()
+        )));
+    }
+
+    let probe = store_dir.join(format!(".barqly-write-probe-{}", std::process::id()));
+    fs::write(&probe, b"").map_err(|_| StorageError::PermissionDenied(store_dir.clone()))?;
+    let _ = fs::remove_file(&probe);
+    Ok(store_dir)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_store_must_be_outside_the_profile() {
+        let temp_dir = TempDir::new().unwrap();
+        let profile = temp_dir.path().join("alice/com.barqly.vault");
+        let shared = temp_dir.path().join("Shared/Barqly");
+        fs::create_dir_all(profile.join("vaults")).unwrap();
+        fs::create_dir_all(&shared).unwrap();
+
+        let store = validate_store_location(&shared, &profile).unwrap();
+        assert_eq!(store, shared.canonicalize().unwrap());
+        assert!(validate_store_location(&profile.join("vaults"), &profile).is_err());
+        assert!(validate_store_location(temp_dir.path(), &profile).is_err());
+        assert!(validate_store_location(Path::new("Shared"), &profile).is_err());
+        assert!(validate_store_location(&temp_dir.path().join("missing"), &profile).is_err());
+
+        let setting_path = profile.join(SHARED_STORE_FILENAME);
+        assert_eq!(
+            SharedStoreSetting::load_from(&setting_path).unwrap(),
+            SharedStoreSetting::default()
+        );
+        let setting = SharedStoreSetting {
+            store_dir: Some(store),
+            ..Default::default()
+        };
+        setting.save_to(&setting_path).unwrap();
+        assert_eq!(
+            SharedStoreSetting::load_from(&setting_path).unwrap(),
+            setting
+        );
+    }
+}
@@ -0,0 +1,299 @@
+//! Storage mode and base directory overrides
+//!
+//! Resolved once at startup from the environment:
+//! - **Portable**: `BARQLY_PORTABLE=1`, or a `portable.marker` file beside the
+//!   executable, puts everything under `./barqly-data` next to the binary.
+//! - **Standard on Linux**: honors `XDG_DATA_HOME` (keys, manifests),
+//!   `XDG_CONFIG_HOME` (settings), `XDG_STATE_HOME` (logs), and
+//!   `XDG_CACHE_HOME` (cache), falling back to the spec defaults under `$HOME`.
+//! - **Standard elsewhere**: platform defaults, no overrides.
+//!
+//! Resolution takes the environment as a lookup function so every
+//! combination can be tested without touching the process environment.
+
+use super::provider::Platform;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Env var that switches on portable mode
+pub const PORTABLE_ENV_VAR: &str = "BARQLY_PORTABLE";
+/// File beside the executable that switches on portable mode
+pub const PORTABLE_MARKER_FILE: &str = "portable.marker";
+/// Folder beside the executable holding all portable data
+pub const PORTABLE_DATA_DIR: &str = "barqly-data";
+
+const APP_DIR_NAME: &str = "com.barqly.vault";
+
+/// Where application data lives
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+#[serde(rename_all = "snake_case")]
+pub enum StorageMode {
+    /// Platform locations (XDG on Linux)
+    Standard,
+    /// Everything next to the executable
+    Portable,
+}
+
+/// Base directories that replace the platform defaults
+///
+/// `None` means "use the platform default" for that directory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StorageLayout {
+    pub mode: StorageMode,
+    /// Keys, vault manifests, backups, device identity
+    pub data_dir: Option<PathBuf>,
+    /// Settings registries
+    pub config_dir: Option<PathBuf>,
+    pub logs_dir: Option<PathBuf>,
+    pub cache_dir: Option<PathBuf>,
+    /// Parent of Barqly-Vaults and Barqly-Recovery
+    pub documents_dir: Option<PathBuf>,
+}
+
+impl StorageLayout {
+    /// Platform defaults for everything
+    pub fn standard() -> Self {
+        Self {
+            mode: StorageMode::Standard,
+            data_dir: None,
+            config_dir: None,
+            logs_dir: None,
+            cache_dir: None,
+            documents_dir: None,
+        }
+    }
+
+    /// All data under `root`
+    pub fn portable(root: PathBuf) -> Self {
+        Self {
+            mode: StorageMode::Portable,
+            data_dir: Some(root.clone()),
+            config_dir: Some(root.join("config")),
+            logs_dir: Some(root.join("logs")),
+            cache_dir: Some(root.join("cache")),
+            documents_dir: Some(root),
+        }
+    }
+
+    /// Resolve the layout for the current process
+    pub fn detect(platform: Platform) -> Self {
+        let exe_dir = std::env::current_exe()
+            .ok()
+            .and_then(|exe| exe.parent().map(Path::to_path_buf));
+        Self::resolve(
+            platform,
+            |name| std::env::var(name).ok(),
+            exe_dir.as_deref(),
+        )
+    }
+
+    /// Resolve the layout from an environment lookup and executable directory
+    pub fn resolve(
+        platform: Platform,
+        env: impl Fn(&str) -> Option<String>,
+        exe_dir: Option<&Path>,
+    ) -> Self {
+        if let Some(exe_dir) = exe_dir
+            && portable_requested(&env, exe_dir)
+        {
+            return Self::portable(exe_dir.join(PORTABLE_DATA_DIR));
+        }
+
+        match platform {
+            Platform::Linux => Self::linux_xdg(&env),
+            Platform::MacOS | Platform::Windows => Self::standard(),
+        }
+    }
+
+    fn linux_xdg(env: &impl Fn(&str) -> Option<String>) -> Self {
+        let home = env("HOME").filter(|h| !h.is_empty()).map(PathBuf::from);
+        // An explicitly set XDG_DATA_HOME is honored; otherwise the platform
+        // default already is ~/.local/share
+        let data_dir = xdg_var(env, "XDG_DATA_HOME").map(|d| d.join(APP_DIR_NAME));
+        let base = |var: &str, default: &[&str]| {
+            xdg_var(env, var)
+                .or_else(|| {
+                    home.as_ref()
+                        .map(|h| default.iter().fold(h.clone(), |p, c| p.join(c)))
+                })
+                .map(|d| d.join(APP_DIR_NAME))
+        };
+
+        Self {
+            mode: StorageMode::Standard,
+            data_dir,
+            config_dir: base("XDG_CONFIG_HOME", &[".config"]),
+            logs_dir: base("XDG_STATE_HOME", &[".local", "state"]).map(|d| d.join("logs")),
+            cache_dir: base("XDG_CACHE_HOME", &[".cache"]),
+            documents_dir: None,
+        }
+    }
+}
+
+/// XDG variables must be absolute; relative values are ignored per the spec
+fn xdg_var(env: &impl Fn(&str) -> Option<String>, name: &str) -> Option<PathBuf> {
+    env(name)
+        .map(PathBuf::from)
+        .filter(|path| path.is_absolute())
+}
+
+fn portable_requested(env: &impl Fn(&str) -> Option<String>, exe_dir: &Path) -> bool {
+    let from_env = env(PORTABLE_ENV_VAR)
+        .is_some_and(|value| matches!(value.trim(), "1" | "true" | "TRUE" | "yes"));
+    from_env || exe_dir.join(PORTABLE_MARKER_FILE).is_file()
+}
+
+/// Warn when portable mode would start an empty profile while the default
+/// location already holds one
+///
+/// Migration between modes is not automatic, so silently starting fresh
+/// would look like lost keys.
+pub fn detect_profile_conflict(portable_root: &Path, default_data_dir: &Path) -> Option<String> {
+    let has_profile = |dir: &Path| {
+        dir.join("device.json").exists() || dir.join("keys").is_dir() || dir.join("vaults").is_dir()
+    };
+
+    (!has_profile(portable_root) && has_profile(default_data_dir)).then(|| {
+        format!(
+            "Portable mode is active, but an existing profile was found at {}. \
+             It will not be used; copy it into {} to keep your keys and vaults.",
+            default_data_dir.display(),
+            portable_root.display()
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use tempfile::TempDir;
+
+    fn env_of(vars: &[(&str, &str)]) -> impl Fn(&str) -> Option<String> {
+        let vars: HashMap<String, String> = vars
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        move |name| vars.get(name).cloned()
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_linux_xdg_defaults_split_data_config_logs() {
+        let exe = TempDir::new().unwrap();
+        let layout = StorageLayout::resolve(
+            Platform::Linux,
+            env_of(&[("HOME", "/home/sam")]),
+            Some(exe.path()),
+        );
+
+        assert_eq!(layout.mode, StorageMode::Standard);
+        assert_eq!(layout.data_dir, None);
+        assert_eq!(
+            layout.config_dir,
+            Some(PathBuf::from("/home/sam/.config/com.barqly.vault"))
+        );
+        assert_eq!(
+            layout.logs_dir,
+            Some(PathBuf::from(
+                "/home/sam/.local/state/com.barqly.vault/logs"
+            ))
+        );
+        assert_eq!(
+            layout.cache_dir,
+            Some(PathBuf::from("/home/sam/.cache/com.barqly.vault"))
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_linux_xdg_overrides() {
+        let root = TempDir::new().unwrap();
+        let dir = |name: &str| root.path().join(name).to_string_lossy().into_owned();
+        let layout = StorageLayout::resolve(
+            Platform::Linux,
+            env_of(&[
+                ("HOME", "/home/sam"),
+                ("XDG_DATA_HOME", &dir("data")),
+                ("XDG_CONFIG_HOME", &dir("config")),
+                ("XDG_STATE_HOME", &dir("state")),
+                ("XDG_CACHE_HOME", "relative/cache"),
+            ]),
+            None,
+        );
+
+        assert_eq!(
+            layout.data_dir,
+            Some(root.path().join("data").join(APP_DIR_NAME))
+        );
+        assert_eq!(
+            layout.config_dir,
+            Some(root.path().join("config").join(APP_DIR_NAME))
+        );
+        assert_eq!(
+            layout.logs_dir,
+            Some(root.path().join("state").join(APP_DIR_NAME).join("logs"))
+        );
+        // Relative XDG values are ignored in favour of the default
+        assert_eq!(
+            layout.cache_dir,
+            Some(PathBuf::from("/home/sam/.cache/com.barqly.vault"))
+        );
+    }
+
+    #[test]
+    fn test_non_linux_ignores_xdg() {
+        let env = env_of(&[("XDG_DATA_HOME", "/xdg/data"), ("HOME", "/home/sam")]);
+        for platform in [Platform::MacOS, Platform::Windows] {
+            assert_eq!(
+                StorageLayout::resolve(platform, &env, None),
+                StorageLayout::standard()
+            );
+        }
+    }
+
+    #[test]
+    fn test_portable_via_env_or_marker() {
+        let exe = TempDir::new().unwrap();
+        let portable_root = exe.path().join(PORTABLE_DATA_DIR);
+
+        let layout = StorageLayout::resolve(
+            Platform::Linux,
+            env_of(&[(PORTABLE_ENV_VAR, "1"), ("XDG_DATA_HOME", "/xdg/data")]),
+            Some(exe.path()),
+        );
+        assert_eq!(layout, StorageLayout::portable(portable_root.clone()));
+        assert_eq!(layout.logs_dir, Some(portable_root.join("logs")));
+        assert_eq!(layout.cache_dir, Some(portable_root.join("cache")));
+
+        let layout = StorageLayout::resolve(
+            Platform::Windows,
+            env_of(&[(PORTABLE_ENV_VAR, "0")]),
+            Some(exe.path()),
+        );
+        assert_eq!(layout.mode, StorageMode::Standard);
+
+        std::fs::write(exe.path().join(PORTABLE_MARKER_FILE), "").unwrap();
+        let layout = StorageLayout::resolve(Platform::MacOS, env_of(&[]), Some(exe.path()));
+        assert_eq!(layout, StorageLayout::portable(portable_root));
+    }
+
+    #[test]
+    fn test_portable_warns_about_existing_default_profile() {
+        let temp = TempDir::new().unwrap();
+        let default_dir = temp.path().join("default");
+        let portable_root = temp.path().join(PORTABLE_DATA_DIR);
+
+        // Nothing anywhere: no warning
+        assert!(detect_profile_conflict(&portable_root, &default_dir).is_none());
+
+        std::fs::create_dir_all(default_dir.join("keys")).unwrap();
+        let warning = detect_profile_conflict(&portable_root, &default_dir).unwrap();
+        assert!(warning.contains(&default_dir.display().to_string()));
+
+        // Once the portable profile exists it's the user's choice
+        std::fs::create_dir_all(&portable_root).unwrap();
+        std::fs::write(portable_root.join("device.json"), "{}").unwrap();
+        assert!(detect_profile_conflict(&portable_root, &default_dir).is_none());
+    }
+}
@@ -10,7 +10,9 @@
 use std::path::PathBuf;
 use std::sync::Once;
 
-use super::directories::{get_manifest_backups_dir, get_vaults_manifest_dir};
+use super::directories::{
+    get_manifest_backups_dir, get_vault_manifest_dirs, get_vaults_manifest_dir,
+};
 
 /// Get the user's Documents directory with headless fallback
 ///
@@ -101,9 +103,16 @@ pub fn get_vault_file_path(vault_name: &str) -> Result<PathBuf, StorageError> {
 ///
 /// # Returns
 /// Path to manifest in non-sync: `~/Library/.../vaults/Vault-001.manifest`
+///
+/// With a shared store, a vault already in the profile stays there and any
+/// other vault's manifest is in the store.
 pub fn get_vault_manifest_path(vault_name: &str) -> Result<PathBuf, StorageError> {
-    let vaults_manifest_dir = get_vaults_manifest_dir()?;
-    Ok(vaults_manifest_dir.join(format!("{vault_name}.manifest")))
+    let filename = format!("{vault_name}.manifest");
+    let own = get_vaults_manifest_dir()?.join(&filename);
+    match get_vault_manifest_dirs()? {
+        dirs if dirs.len() > 1 && !own.exists() => Ok(dirs[0].join(filename)),
+        _ => Ok(own),
+    }
 }
 
 /// Get the recovery path for a specific vault
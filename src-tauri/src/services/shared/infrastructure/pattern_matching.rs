@@ -0,0 +1,104 @@
+//! Glob-style pattern matching for file paths
+//!
+//! Small, dependency-free matcher used by vault templates and file filters.
+//! Supports `*` (any run of characters) and `?` (single character), matched
+//! case-insensitively.
+//!
+//! Patterns without a `/` are matched against the file name only; patterns
+//! containing `/` are matched against the whole relative path (with `\`
+//! normalized to `/`).
+
+/// Check whether a relative path matches a glob pattern
+///
+/// # Examples
+/// ```ignore
+/// assert!(path_matches_glob("*.pdf", "taxes/2019/return.PDF"));
+/// assert!(path_matches_glob("wallets/*", "wallets/cold.json"));
+/// assert!(!path_matches_glob("*.pdf", "notes.txt"));
+/// ```
+pub fn path_matches_glob(pattern: &str, relative_path: &str) -> bool {
+    let normalized = relative_path.replace('\\', "/");
+    let pattern = pattern.trim();
+
+    if pattern.contains('/') {
+        glob_match(pattern, &normalized)
+    } else {
+        let file_name = normalized.rsplit('/').next().unwrap_or(&normalized);
+        glob_match(pattern, file_name)
+    }
+}
+
+/// Check whether a path matches any of the given glob patterns
+pub fn path_matches_any(patterns: &[String], relative_path: &str) -> bool {
+    patterns.iter().any(|p| path_matches_glob(p, relative_path))
+}
+
+/// Case-insensitive wildcard match (`*` and `?`) using iterative backtracking
+pub fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.to_lowercase().chars().collect();
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+
+    let (mut p, mut t) = (0usize, 0usize);
+    let mut star: Option<usize> = None;
+    let mut star_text = 0usize;
+
+    while t < text.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == text[t]) {
+            p += 1;
+            t += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            star = Some(p);
+            star_text = t;
+            p += 1;
+        } else if let Some(star_pos) = star {
+            p = star_pos + 1;
+            star_text += 1;
+            t = star_text;
+        } else {
+            return false;
+        }
+    }
+
+    while p < pattern.len() && pattern[p] == '*' {
+        p += 1;
+    }
+
+    p == pattern.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_glob_match_wildcards() {
+        assert!(glob_match("*.pdf", "report.pdf"));
+        assert!(glob_match("*seed*", "my-seed-phrase.txt"));
+        assert!(glob_match("file?.txt", "file1.txt"));
+        assert!(!glob_match("file?.txt", "file10.txt"));
+        assert!(glob_match("*", ""));
+        assert!(!glob_match("a*b", "acbd"));
+    }
+
+    #[test]
+    fn test_case_insensitive() {
+        assert!(glob_match("*.PDF", "return.pdf"));
+        assert!(glob_match("Wallet*", "wallet.dat"));
+    }
+
+    #[test]
+    fn test_file_name_vs_path_patterns() {
+        assert!(path_matches_glob("*.pdf", "taxes/2019/return.pdf"));
+        assert!(path_matches_glob("taxes/*", "taxes/return.pdf"));
+        assert!(!path_matches_glob("taxes/*", "other/return.pdf"));
+        assert!(path_matches_glob("*.json", "wallets\\cold.json"));
+    }
+
+    #[test]
+    fn test_matches_any() {
+        let patterns = vec!["*.pdf".to_string(), "*.docx".to_string()];
+        assert!(path_matches_any(&patterns, "will.docx"));
+        assert!(!path_matches_any(&patterns, "photo.jpg"));
+        assert!(!path_matches_any(&[], "photo.jpg"));
+    }
+}
@@ -0,0 +1,286 @@
+//! Active operation registry
+//!
+//! Tracks which long-running operations are currently running. Ordinary
+//! operations may overlap, but an exclusive one (a benchmark) only starts
+//! when nothing else is running, and nothing else starts until it finishes.
+//! An operation stays registered until its guard is dropped.
+//!
+//! Running operations can be asked to stop with `cancel_operations`. The
+//! request is cooperative: an operation checks `OperationGuard::is_cancelled`
+//! at the points where stopping is safe. `wait_until_idle` waits for them to
+//! wind down, e.g. before the app quits.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::sync::{Condvar, Mutex, MutexGuard, OnceLock};
+use std::time::Duration;
+
+/// Kind of long-running operation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, specta::Type)]
+#[serde(rename_all = "snake_case")]
+pub enum OperationKind {
+    Encryption,
+    Decryption,
+    BatchDecryption,
+    Maintenance,
+    KeyReplacement,
+    Benchmark,
+}
+
+impl fmt::Display for OperationKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Encryption => "encryption",
+            Self::Decryption => "decryption",
+            Self::BatchDecryption => "batch decryption",
+            Self::Maintenance => "vault maintenance",
+            Self::KeyReplacement => "YubiKey replacement",
+            Self::Benchmark => "benchmark",
+        };
+        f.write_str(name)
+    }
+}
+
+/// An operation couldn't start because of what is already running
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OperationConflict {
+    pub requested: OperationKind,
+    pub active: Vec<OperationKind>,
+}
+
+impl fmt::Display for OperationConflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let active: Vec<String> = self.active.iter().map(ToString::to_string).collect();
+        write!(
+            f,
+            "Can't start {} while {} is running",
+            self.requested,
+            active.join(", ")
+        )
+    }
+}
+
+impl std::error::Error for OperationConflict {}
+
+/// A registered operation and what it works on
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RunningOperation {
+    pub kind: OperationKind,
+    /// Vault the operation writes to or reads from, when it has one
+    pub vault_id: Option<String>,
+    /// ID its progress is published under
+    pub progress_id: Option<String>,
+    pub started_at: DateTime<Utc>,
+}
+
+#[derive(Default)]
+struct ActiveOperations {
+    next_id: u64,
+    running: HashMap<u64, RunningOperation>,
+    /// Set while an exclusive operation runs
+    exclusive: Option<u64>,
+    /// Running operations asked to stop
+    cancelled: HashSet<u64>,
+}
+
+/// Registration of a running operation, released on drop
+#[derive(Debug)]
+#[must_use = "the operation is unregistered as soon as the guard is dropped"]
+pub struct OperationGuard {
+    id: u64,
+    kind: OperationKind,
+}
+
+impl OperationGuard {
+    pub fn kind(&self) -> OperationKind {
+        self.kind
+    }
+
+    /// Whether the operation was asked to stop
+    pub fn is_cancelled(&self) -> bool {
+        lock_active().cancelled.contains(&self.id)
+    }
+
+    /// Record which vault the operation works on
+    pub fn set_vault(&self, vault_id: &str) {
+        if let Some(operation) = lock_active().running.get_mut(&self.id) {
+            operation.vault_id = Some(vault_id.to_string());
+        }
+    }
+
+    /// Record the ID the operation publishes its progress under
+    pub fn set_progress_id(&self, progress_id: &str) {
+        if let Some(operation) = lock_active().running.get_mut(&self.id) {
+            operation.progress_id = Some(progress_id.to_string());
+        }
+    }
+}
+
+impl Drop for OperationGuard {
+    fn drop(&mut self) {
+        let mut active = lock_active();
+        active.running.remove(&self.id);
+        active.cancelled.remove(&self.id);
+        if active.exclusive == Some(self.id) {
+            active.exclusive = None;
+        }
+        if active.running.is_empty() {
+            idle().notify_all();
+        }
+    }
+}
+
+fn active() -> &'static Mutex<ActiveOperations> {
+    static ACTIVE: OnceLock<Mutex<ActiveOperations>> = OnceLock::new();
+    ACTIVE.get_or_init(Mutex::default)
+}
+
+fn lock_active() -> MutexGuard<'static, ActiveOperations> {
+    active().lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Signalled when the last running operation ends
+fn idle() -> &'static Condvar {
+    static IDLE: OnceLock<Condvar> = OnceLock::new();
+    IDLE.get_or_init(Condvar::new)
+}
+
+fn register(active: &mut ActiveOperations, kind: OperationKind) -> OperationGuard {
+    active.next_id += 1;
+    let id = active.next_id;
+    active.running.insert(
+        id,
+        RunningOperation {
+            kind,
+            vault_id: None,
+            progress_id: None,
+            started_at: Utc::now(),
+        },
+    );
+    OperationGuard { id, kind }
+}
+
+/// Register an operation that may run alongside others
+///
+/// Fails only while an exclusive operation is running.
+pub fn begin_operation(kind: OperationKind) -> Result<OperationGuard, OperationConflict> {
+    let mut active = lock_active();
+    if let Some(exclusive) = active.exclusive {
+        return Err(OperationConflict {
+            requested: kind,
+            active: vec![active.running[&exclusive].kind],
+        });
+    }
+    Ok(register(&mut active, kind))
+}
+
+/// Register an operation that must run alone
+///
+/// Fails if any other operation is running.
+pub fn begin_exclusive_operation(kind: OperationKind) -> Result<OperationGuard, OperationConflict> {
+    let mut active = lock_active();
+    if !active.running.is_empty() {
+        return Err(OperationConflict {
+            requested: kind,
+            active: active.running.values().map(|o| o.kind).collect(),
+        });
+    }
+    let guard = register(&mut active, kind);
+    active.exclusive = Some(guard.id);
+    Ok(guard)
+}
+
+/// Operations currently running
+pub fn active_operations() -> Vec<OperationKind> {
+    lock_active().running.values().map(|o| o.kind).collect()
+}
+
+/// Operations currently running with what they work on, oldest first
+pub fn running_operations() -> Vec<RunningOperation> {
+    let active = lock_active();
+    let mut ids: Vec<&u64> = active.running.keys().collect();
+    ids.sort();
+    ids.into_iter()
+        .map(|id| active.running[id].clone())
+        .collect()
+}
+
+/// Ask every running operation to stop; returns those newly asked
+pub fn cancel_operations() -> Vec<OperationKind> {
+    let mut active = lock_active();
+    let ids: Vec<u64> = active.running.keys().copied().collect();
+    let mut cancelled = Vec::new();
+    for id in ids {
+        if active.cancelled.insert(id) {
+            cancelled.push(active.running[&id].kind);
+        }
+    }
+    cancelled
+}
+
+/// Wait up to `timeout` for every running operation to end; false if some
+/// were still running when it passed
+pub fn wait_until_idle(timeout: Duration) -> bool {
+    let (active, _) = idle()
+        .wait_timeout_while(lock_active(), timeout, |a| !a.running.is_empty())
+        .unwrap_or_else(|e| e.into_inner());
+    active.running.is_empty()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // One test, since the registry is process-wide
+    #[test]
+    fn test_exclusive_operation_runs_alone() {
+        let encryption = begin_operation(OperationKind::Encryption).unwrap();
+        let conflict = begin_exclusive_operation(OperationKind::Benchmark).unwrap_err();
+        assert_eq!(conflict.active, vec![OperationKind::Encryption]);
+
+        drop(encryption);
+        let benchmark = begin_exclusive_operation(OperationKind::Benchmark).unwrap();
+        assert_eq!(benchmark.kind(), OperationKind::Benchmark);
+
+        let conflict = begin_operation(OperationKind::Decryption).unwrap_err();
+        assert_eq!(
+            conflict.to_string(),
+            "Can't start decryption while benchmark is running"
+        );
+
+        drop(benchmark);
+        assert!(active_operations().is_empty());
+        let decryption = begin_operation(OperationKind::Decryption).unwrap();
+        let batch = begin_operation(OperationKind::BatchDecryption).unwrap();
+        assert_eq!(active_operations().len(), 2);
+
+        decryption.set_vault("vault-1");
+        decryption.set_progress_id("decrypt_1");
+        let running = running_operations();
+        assert_eq!(running[0].kind, OperationKind::Decryption);
+        assert_eq!(running[0].vault_id.as_deref(), Some("vault-1"));
+        assert_eq!(running[0].progress_id.as_deref(), Some("decrypt_1"));
+        assert_eq!(running[1].vault_id, None);
+
+        assert!(!decryption.is_cancelled());
+        assert_eq!(cancel_operations().len(), 2);
+        assert!(decryption.is_cancelled());
+        // Already-cancelled operations aren't reported twice
+        assert!(cancel_operations().is_empty());
+
+        // Waiting ends once the last operation winds down
+        assert!(!wait_until_idle(Duration::from_millis(10)));
+        let stopping = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(20));
+            drop(batch);
+        });
+        drop(decryption);
+        assert!(wait_until_idle(Duration::from_secs(5)));
+        stopping.join().unwrap();
+
+        let encryption = begin_operation(OperationKind::Encryption).unwrap();
+        assert!(!encryption.is_cancelled());
+    }
+}
@@ -1,12 +1,19 @@
 //! Global progress tracking for long-running operations
 //!
 //! Provides centralized progress state management that can be queried
-//! by progress commands and updated by service operations.
+//! by progress commands and updated by service operations. Every update is
+//! also broadcast to all windows, so any window can follow any operation.
 
+use crate::services::key_management::yubikey::infrastructure::pty::app_handle::get_app_handle;
 use crate::types::ProgressUpdate;
 use std::collections::HashMap;
 use std::sync::Mutex;
 use std::sync::atomic::AtomicBool;
+use tauri::Emitter;
+
+/// App-wide event carrying a `ProgressUpdate`; its `operation_id` says which
+/// operation it belongs to
+pub const OPERATION_PROGRESS_EVENT: &str = "operation-progress";
 
 /// Global operation state to prevent race conditions
 pub static ENCRYPTION_IN_PROGRESS: AtomicBool = AtomicBool::new(false);
@@ -15,8 +22,13 @@
 pub static PROGRESS_TRACKER: once_cell::sync::Lazy<Mutex<HashMap<String, ProgressUpdate>>> =
     once_cell::sync::Lazy::new(|| Mutex::new(HashMap::new()));
 
-/// Update global progress for an operation
+/// Update global progress for an operation and broadcast it to every window
 pub fn update_global_progress(operation_id: &str, progress: ProgressUpdate) {
+    if let Some(app) = get_app_handle()
+        && let Err(e) = app.emit(OPERATION_PROGRESS_EVENT, &progress)
+    {
+        tracing::warn!(operation_id, error = %e, "Failed to emit progress event");
+    }
     if let Ok(mut tracker) = PROGRESS_TRACKER.lock() {
         tracker.insert(operation_id.to_string(), progress);
     }
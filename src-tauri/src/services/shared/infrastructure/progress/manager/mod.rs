@@ -7,6 +7,8 @@
 
 use self::debouncer::ProgressDebouncer;
 use self::utils::{create_progress_update, progress_to_fraction, progress_to_percentage};
+use crate::logging::current_trace_id;
+use crate::services::shared::infrastructure::io::OperationPriority;
 use crate::types::{ProgressCallback, ProgressDetails, ProgressUpdate};
 
 /// Progress manager for tracking and reporting operation progress with debouncing
@@ -19,6 +21,9 @@ pub struct ProgressManager {
     current_message: String,
     current_details: Option<ProgressDetails>,
     debouncer: ProgressDebouncer,
+    io_priority: OperationPriority,
+    /// Trace ID of the command that created the manager
+    trace_id: Option<String>,
 }
 
 impl ProgressManager {
@@ -34,6 +39,8 @@ pub fn new(operation_id: String, total_work: u64) -> Self {
             current_message: "Starting operation...".to_string(),
             current_details: None,
             debouncer: ProgressDebouncer::new(),
+            io_priority: OperationPriority::default(),
+            trace_id: current_trace_id(),
         }
     }
 
@@ -43,6 +50,17 @@ pub fn with_callback(mut self, callback: ProgressCallback) -> Self {
         self
     }
 
+    /// Report the operation's I/O priority, following later switches
+    pub fn with_io_priority(mut self, io_priority: OperationPriority) -> Self {
+        self.io_priority = io_priority;
+        self
+    }
+
+    /// Priority handle the operation's I/O should be paced by
+    pub fn io_priority(&self) -> OperationPriority {
+        self.io_priority.clone()
+    }
+
     /// Update progress with completed work
     pub fn update_progress(&mut self, completed: u64, message: impl Into<String>) {
         self.completed_work = completed;
@@ -90,14 +108,7 @@ pub fn complete(&mut self, message: impl Into<String>) {
 
     /// Report current progress to callback with debouncing
     fn report_progress(&mut self) {
-        let update = create_progress_update(
-            &self.operation_id,
-            self.completed_work,
-            self.total_work,
-            &self.current_message,
-            self.current_details.as_ref(),
-            self.start_time,
-        );
+        let update = self.get_current_update();
 
         self.debouncer.process_update(update);
         self.last_update = chrono::Utc::now();
@@ -120,14 +131,18 @@ pub fn progress_fraction(&self) -> f32 {
 
     /// Get current progress update
     pub fn get_current_update(&self) -> ProgressUpdate {
-        create_progress_update(
-            &self.operation_id,
-            self.completed_work,
-            self.total_work,
-            &self.current_message,
-            self.current_details.as_ref(),
-            self.start_time,
-        )
+        ProgressUpdate {
+            trace_id: self.trace_id.clone(),
+            ..create_progress_update(
+                &self.operation_id,
+                self.completed_work,
+                self.total_work,
+                &self.current_message,
+                self.current_details.as_ref(),
+                self.start_time,
+                self.io_priority.get(),
+            )
+        }
     }
 }
 
@@ -280,4 +295,24 @@ fn should_handle_zero_total_work_gracefully() {
             assert_eq!(update.progress, 0.0);
         }
     }
+
+    #[test]
+    fn should_report_current_io_priority() {
+        use crate::types::IoPriority;
+
+        let priority = OperationPriority::new(IoPriority::Normal);
+        let mut progress_manager =
+            ProgressManager::new("test_op".to_string(), 100).with_io_priority(priority.clone());
+        progress_manager.set_progress(0.1, "Encrypting");
+        assert_eq!(
+            progress_manager.get_current_update().io_priority,
+            IoPriority::Normal
+        );
+
+        priority.set(IoPriority::Background);
+        assert_eq!(
+            progress_manager.get_current_update().io_priority,
+            IoPriority::Background
+        );
+    }
 }
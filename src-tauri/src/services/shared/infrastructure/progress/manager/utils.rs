@@ -4,28 +4,28 @@
 //! ETAs, and formatting progress information.
 
 use crate::constants::PROGRESS_PERCENTAGE_MULTIPLIER;
-use crate::types::{ProgressDetails, ProgressUpdate};
+use crate::types::{DurationMs, IoPriority, ProgressDetails, ProgressUpdate};
 
 /// Calculate estimated time remaining based on progress
 pub fn calculate_eta(
     completed_work: u64,
     total_work: u64,
     start_time: chrono::DateTime<chrono::Utc>,
-) -> Option<u64> {
+) -> Option<DurationMs> {
     if completed_work == 0 || total_work == 0 {
         return None;
     }
 
-    let elapsed = (chrono::Utc::now() - start_time).num_seconds() as u64;
-    if elapsed == 0 {
+    let elapsed_ms = (chrono::Utc::now() - start_time).num_milliseconds();
+    if elapsed_ms <= 0 {
         return None;
     }
 
-    let rate = completed_work as f64 / elapsed as f64;
-    let remaining_work = total_work - completed_work;
+    let rate = completed_work as f64 / elapsed_ms as f64;
+    let remaining_work = total_work.saturating_sub(completed_work);
     let eta = (remaining_work as f64 / rate) as u64;
 
-    Some(eta)
+    Some(DurationMs(eta))
 }
 
 /// Convert progress to percentage (0-100)
@@ -54,6 +54,7 @@ pub fn create_progress_update(
     message: &str,
     details: Option<&ProgressDetails>,
     start_time: chrono::DateTime<chrono::Utc>,
+    io_priority: IoPriority,
 ) -> ProgressUpdate {
     let progress = progress_to_fraction(completed_work, total_work);
     let estimated_time_remaining = calculate_eta(completed_work, total_work, start_time);
@@ -65,5 +66,7 @@ pub fn create_progress_update(
         details: details.cloned(),
         timestamp: chrono::Utc::now(),
         estimated_time_remaining,
+        io_priority,
+        trace_id: None,
     }
 }
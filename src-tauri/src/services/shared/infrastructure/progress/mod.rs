@@ -3,12 +3,21 @@
 //! This module provides:
 //! - ProgressManager: Debounced progress reporting for efficient UI updates
 //! - Global progress state: Centralized tracking for querying operation status
+//! - Active operations: Keeps exclusive work (benchmarks) from overlapping others,
+//!   and asks running work to stop (and waits for it, before quitting)
 
+pub mod activity;
 pub mod global;
 pub mod manager;
 
 // Re-export for convenience
+pub use activity::{
+    OperationConflict, OperationGuard, OperationKind, RunningOperation, active_operations,
+    begin_exclusive_operation, begin_operation, cancel_operations, running_operations,
+    wait_until_idle,
+};
 pub use global::{
-    ENCRYPTION_IN_PROGRESS, PROGRESS_TRACKER, get_global_progress, update_global_progress,
+    ENCRYPTION_IN_PROGRESS, OPERATION_PROGRESS_EVENT, PROGRESS_TRACKER, get_global_progress,
+    update_global_progress,
 };
 pub use manager::ProgressManager;
@@ -0,0 +1,117 @@
+//! Per-window session state
+//!
+//! Each app window keeps its own current-vault pointer, keyed by the Tauri
+//! window label, so two windows can work in different vaults side by side.
+//! Operation state (progress, the active-operation registry) stays global and
+//! reads the same from every window.
+//!
+//! Contexts without a window, such as deep links, use the default vault: the
+//! one most recently selected in any window still open.
+
+use std::collections::HashMap;
+use std::sync::{Mutex, MutexGuard, OnceLock};
+
+#[derive(Debug, Default)]
+struct SessionState {
+    /// Current vault id per window label
+    current: HashMap<String, String>,
+    /// Window labels, least recently selected first
+    recency: Vec<String>,
+}
+
+/// Current-vault pointers for every open window
+#[derive(Debug, Default)]
+pub struct WindowSessions {
+    state: Mutex<SessionState>,
+}
+
+impl WindowSessions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sessions shared by the app's windows and commands
+    pub fn global() -> &'static Self {
+        static SESSIONS: OnceLock<WindowSessions> = OnceLock::new();
+        SESSIONS.get_or_init(Self::new)
+    }
+
+    fn lock(&self) -> MutexGuard<'_, SessionState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Point `window_label` at `vault_id`, making it the default vault
+    pub fn set_current_vault(&self, window_label: &str, vault_id: &str) {
+        let mut state = self.lock();
+        state
+            .current
+            .insert(window_label.to_string(), vault_id.to_string());
+        state.recency.retain(|label| label != window_label);
+        state.recency.push(window_label.to_string());
+    }
+
+    /// Vault selected in `window_label`, if any
+    pub fn current_vault(&self, window_label: &str) -> Option<String> {
+        self.lock().current.get(window_label).cloned()
+    }
+
+    /// Vault most recently selected in any open window
+    pub fn default_vault(&self) -> Option<String> {
+        let state = self.lock();
+        state
+            .recency
+            .iter()
+            .rev()
+            .find_map(|label| state.current.get(label).cloned())
+    }
+
+    /// Drop the session of a closed window
+    pub fn close_window(&self, window_label: &str) {
+        let mut state = self.lock();
+        state.current.remove(window_label);
+        state.recency.retain(|label| label != window_label);
+    }
+
+    /// Clear every pointer to a deleted vault
+    pub fn forget_vault(&self, vault_id: &str) {
+        let mut state = self.lock();
+        state.current.retain(|_, current| current != vault_id);
+        let SessionState { current, recency } = &mut *state;
+        recency.retain(|label| current.contains_key(label));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_vault_follows_most_recent_selection() {
+        let sessions = WindowSessions::new();
+        assert_eq!(sessions.default_vault(), None);
+
+        sessions.set_current_vault("main", "vault-a");
+        sessions.set_current_vault("second", "vault-b");
+        assert_eq!(sessions.default_vault().as_deref(), Some("vault-b"));
+
+        sessions.set_current_vault("main", "vault-c");
+        assert_eq!(sessions.default_vault().as_deref(), Some("vault-c"));
+
+        sessions.close_window("main");
+        assert_eq!(sessions.default_vault().as_deref(), Some("vault-b"));
+        assert_eq!(sessions.current_vault("main"), None);
+    }
+
+    #[test]
+    fn test_forget_vault_clears_every_window() {
+        let sessions = WindowSessions::new();
+        sessions.set_current_vault("main", "vault-a");
+        sessions.set_current_vault("second", "vault-a");
+        sessions.set_current_vault("third", "vault-b");
+
+        sessions.forget_vault("vault-a");
+        assert_eq!(sessions.current_vault("main"), None);
+        assert_eq!(sessions.current_vault("second"), None);
+        assert_eq!(sessions.default_vault().as_deref(), Some("vault-b"));
+    }
+}
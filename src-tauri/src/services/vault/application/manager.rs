@@ -1,16 +1,103 @@
-use super::services::VaultService;
-use crate::services::vault::domain::VaultResult;
-use crate::services::vault::domain::models::VaultSummary;
+use super::services::{
+    ArchivePatchService, ArchiveRepairService, ArchiveService, ClipboardBackend, ClipboardCopy,
+    ClipboardGuard, CompatibilityService, DeadMansSwitchService, DirectoryComparisonService,
+    EjectSafetyService, FORCED_QUIT_TIMEOUT, FileSearchService, HookService, InventoryService,
+    LaunchWatchdog, MaintenanceService, MaintenanceTarget, MetadataSnapshotService,
+    NotificationService, OnboardingService, OperationLogService, ProtectionStatus,
+    QuarantineService, RetentionService, SharedStoreService, ShutdownService,
+    StatisticsHistoryService, StorageQuotaService, TransformSettingsService, VaultConflictService,
+    VaultItemService, VaultOrderService, VaultRiskService, VaultService, VaultTemplateService,
+};
+use crate::services::file::infrastructure::file_operations::TransformSettings;
+use crate::services::vault::domain::models::{
+    ArchiveIndexEntry, ArchiveListing, ArchivePatchEntry, ArchivePruneReport, ArchiveRepairReport,
+    ArchiveSearchMatch, ClipboardGuardStatus, ClipboardKind, ClipboardSettings,
+    CompatibilityReport, ConflictResolution, ConflictStrategy, DeadMansSwitchInput,
+    DeadMansSwitchStatus, DirectoryComparison, EjectPreparation, FileSearchResults,
+    FileSearchScope, ForcedQuitReport, FrontendHandshake, HookContext, HookEvent,
+    IncompleteArchiveReport, InventoryExportResult, InventoryFormat, MaintenanceReport,
+    MaintenanceScope, MaintenanceTask, MetadataRestoreResult, MetadataSnapshotDiff,
+    MetadataSnapshotSummary, MilestoneStatus, NotificationPreferences, OnboardingStatus,
+    PatchReplacement, QuarantinePurgeReport, RetentionEvaluation, RetentionPolicy,
+    SensitiveDirectoryStatus, SharedStoreStatus, ShutdownCheck, StatisticsRange,
+    StorageCleanupReport, StorageUsageReport, VaultHooks, VaultItem, VaultItemInput, VaultItemView,
+    VaultNotification, VaultRiskAssessment, VaultStatisticsHistory, VaultSummary, VaultTemplate,
+};
+use crate::services::vault::domain::{VaultError, VaultResult};
+use crate::services::vault::infrastructure::HookRunOutcome;
 use crate::services::vault::infrastructure::persistence::metadata::VaultMetadata;
+use crate::services::vault::infrastructure::persistence::{AppConfig, OperationLogVerification};
+use crate::types::DurationMs;
+use chrono::{DateTime, Utc};
+use std::path::{Path, PathBuf};
+use tracing::warn;
 
 pub struct VaultManager {
     vault_service: VaultService,
+    template_service: VaultTemplateService,
+    notification_service: NotificationService,
+    dead_mans_switch_service: DeadMansSwitchService,
+    archive_service: ArchiveService,
+    retention_service: RetentionService,
+    statistics_history_service: StatisticsHistoryService,
+    repair_service: ArchiveRepairService,
+    patch_service: ArchivePatchService,
+    file_search_service: FileSearchService,
+    maintenance_service: MaintenanceService,
+    item_service: VaultItemService,
+    onboarding_service: OnboardingService,
+    compatibility_service: CompatibilityService,
+    comparison_service: DirectoryComparisonService,
+    inventory_service: InventoryService,
+    quarantine_service: QuarantineService,
+    storage_quota_service: StorageQuotaService,
+    snapshot_service: MetadataSnapshotService,
+    hook_service: HookService,
+    transform_service: TransformSettingsService,
+    risk_service: VaultRiskService,
+    operation_log_service: OperationLogService,
+    order_service: VaultOrderService,
+    shared_store_service: SharedStoreService,
+    shutdown_service: ShutdownService,
+    conflict_service: VaultConflictService,
+    eject_service: EjectSafetyService,
+    launch_watchdog: &'static LaunchWatchdog,
+    clipboard_guard: &'static ClipboardGuard,
 }
 
 impl VaultManager {
     pub fn new() -> Self {
         Self {
             vault_service: VaultService::new(),
+            template_service: VaultTemplateService::new(),
+            notification_service: NotificationService::new(),
+            dead_mans_switch_service: DeadMansSwitchService::new(),
+            archive_service: ArchiveService::new(),
+            retention_service: RetentionService::new(),
+            statistics_history_service: StatisticsHistoryService::new(),
+            repair_service: ArchiveRepairService::new(),
+            patch_service: ArchivePatchService::new(),
+            file_search_service: FileSearchService::new(),
+            maintenance_service: MaintenanceService::new(),
+            item_service: VaultItemService::new(),
+            onboarding_service: OnboardingService::new(),
+            compatibility_service: CompatibilityService::new(),
+            comparison_service: DirectoryComparisonService::new(),
+            inventory_service: InventoryService::new(),
+            quarantine_service: QuarantineService::new(),
+            storage_quota_service: StorageQuotaService::new(),
+            snapshot_service: MetadataSnapshotService::new(),
+            hook_service: HookService::new(),
+            transform_service: TransformSettingsService::new(),
+            risk_service: VaultRiskService::new(),
+            operation_log_service: OperationLogService::new(),
+            order_service: VaultOrderService::new(),
+            shared_store_service: SharedStoreService::new(),
+            shutdown_service: ShutdownService::new(),
+            conflict_service: VaultConflictService::new(),
+            eject_service: EjectSafetyService::new(),
+            launch_watchdog: LaunchWatchdog::global(),
+            clipboard_guard: ClipboardGuard::global(),
         }
     }
 
@@ -19,13 +106,656 @@ pub async fn create_vault(
         &self,
         name: String,
         description: Option<String>,
+        template_id: Option<String>,
     ) -> VaultResult<VaultSummary> {
-        self.vault_service.create_vault(name, description).await
+        self.vault_service
+            .create_vault(name, description, template_id)
+            .await
+    }
+
+    /// List built-in vault templates
+    pub fn list_vault_templates(&self) -> VaultResult<Vec<VaultTemplate>> {
+        self.template_service.list_templates()
+    }
+
+    /// Get protection status (policy, freshness, checklist) for a vault
+    pub async fn get_protection_status(&self, vault_id: &str) -> VaultResult<ProtectionStatus> {
+        let mut status = self.vault_service.get_protection_status(vault_id).await?;
+        status.dead_mans_switch = self.dead_mans_switch_service.get_status(vault_id)?;
+        self.template_service.apply_pending_yubikeys(&mut status)?;
+        Ok(status)
+    }
+
+    /// Choose whether reserved YubiKey slots hold back a vault's protection
+    /// policy, returning the updated status
+    pub async fn set_allow_pending_yubikeys(
+        &self,
+        vault_id: &str,
+        allowed: bool,
+    ) -> VaultResult<ProtectionStatus> {
+        self.vault_service.get_vault(vault_id).await?;
+        self.template_service
+            .set_allow_pending_yubikeys(vault_id, allowed)?;
+        self.get_protection_status(vault_id).await
+    }
+
+    /// Assess whether a vault's keys could all be lost together
+    pub async fn assess_vault_risk(&self, vault_id: &str) -> VaultResult<VaultRiskAssessment> {
+        let metadata = self.vault_service.get_vault(vault_id).await?;
+        self.risk_service.assess(&metadata)
+    }
+
+    /// Check a vault's operation log chain and its head in the manifest
+    pub fn verify_operation_log(&self, vault_id: &str) -> VaultResult<OperationLogVerification> {
+        self.operation_log_service.verify(vault_id)
+    }
+
+    /// Evaluate notification rules across vaults (deduplicated, highest priority first)
+    pub fn get_notifications(&self) -> VaultResult<Vec<VaultNotification>> {
+        self.notification_service.get_notifications()
+    }
+
+    /// Snooze a notification, returning when it may reappear
+    pub fn dismiss_notification(
+        &self,
+        notification_id: &str,
+        snooze_days: u32,
+    ) -> VaultResult<DateTime<Utc>> {
+        self.notification_service
+            .dismiss_notification(notification_id, snooze_days)
+    }
+
+    /// Get a vault's notification preferences
+    pub async fn get_notification_preferences(
+        &self,
+        vault_id: &str,
+    ) -> VaultResult<NotificationPreferences> {
+        self.vault_service.get_vault(vault_id).await?;
+        self.notification_service.get_preferences(vault_id)
+    }
+
+    /// Replace a vault's notification preferences
+    pub async fn update_notification_preferences(
+        &self,
+        vault_id: &str,
+        preferences: NotificationPreferences,
+    ) -> VaultResult<NotificationPreferences> {
+        self.vault_service.get_vault(vault_id).await?;
+        self.notification_service
+            .update_preferences(vault_id, preferences)
+    }
+
+    /// Get a vault's dead man's switch evaluated now (none if never set)
+    pub async fn get_dead_mans_switch_status(
+        &self,
+        vault_id: &str,
+    ) -> VaultResult<Option<DeadMansSwitchStatus>> {
+        self.vault_service.get_vault(vault_id).await?;
+        self.dead_mans_switch_service.get_status(vault_id)
+    }
+
+    /// Set up or change a vault's dead man's switch, or remove it with `None`
+    pub async fn set_dead_mans_switch(
+        &self,
+        vault_id: &str,
+        input: Option<DeadMansSwitchInput>,
+    ) -> VaultResult<Option<DeadMansSwitchStatus>> {
+        self.vault_service.get_vault(vault_id).await?;
+        self.dead_mans_switch_service.set_switch(vault_id, input)
+    }
+
+    /// Restart a vault's check-in interval once the typed confirmation matches
+    pub async fn check_in(
+        &self,
+        vault_id: &str,
+        confirmation: Option<&str>,
+    ) -> VaultResult<DeadMansSwitchStatus> {
+        self.vault_service.get_vault(vault_id).await?;
+        self.dead_mans_switch_service
+            .check_in(vault_id, confirmation)
+    }
+
+    /// Get a vault's post-operation hooks
+    pub async fn get_vault_hooks(&self, vault_id: &str) -> VaultResult<VaultHooks> {
+        self.vault_service.get_vault(vault_id).await?;
+        self.hook_service.get_hooks(vault_id)
+    }
+
+    /// Validate and replace a vault's post-operation hooks
+    pub async fn update_vault_hooks(
+        &self,
+        vault_id: &str,
+        hooks: VaultHooks,
+    ) -> VaultResult<VaultHooks> {
+        self.vault_service.get_vault(vault_id).await?;
+        self.hook_service.update_hooks(vault_id, hooks)
+    }
+
+    /// Get a vault's pre-encryption transform rules
+    pub async fn get_vault_transforms(&self, vault_id: &str) -> VaultResult<TransformSettings> {
+        self.vault_service.get_vault(vault_id).await?;
+        self.transform_service.get_transforms(vault_id)
+    }
+
+    /// Validate and replace a vault's pre-encryption transform rules
+    pub async fn update_vault_transforms(
+        &self,
+        vault_id: &str,
+        transforms: TransformSettings,
+    ) -> VaultResult<TransformSettings> {
+        self.vault_service.get_vault(vault_id).await?;
+        self.transform_service
+            .update_transforms(vault_id, transforms)
+    }
+
+    /// Run one of a vault's hooks with placeholder values
+    pub async fn test_hook(&self, vault_id: &str, hook_id: &str) -> VaultResult<HookRunOutcome> {
+        let vault = self.vault_service.get_vault(vault_id).await?;
+        self.hook_service
+            .test_hook(vault_id, vault.label(), hook_id)
     }
 
-    /// List all vaults
+    /// Run a vault's hooks for a finished operation (see `HookService::dispatch`)
+    pub async fn run_vault_hooks(
+        &self,
+        event: HookEvent,
+        vault_id: &str,
+        archive_path: &str,
+        status: &str,
+    ) {
+        let vault_name = match self.vault_service.get_vault(vault_id).await {
+            Ok(vault) => vault.label().to_string(),
+            Err(e) => {
+                warn!(vault_id, error = %e, "Skipping vault hooks: vault not found");
+                return;
+            }
+        };
+        let context = HookContext {
+            event,
+            vault_id: vault_id.to_string(),
+            vault_name,
+            archive_path: archive_path.to_string(),
+            status: status.to_string(),
+        };
+        self.hook_service.dispatch(context).await
+    }
+
+    /// Search a vault's archives by comment, archive name, or date
+    pub async fn search_archives(
+        &self,
+        vault_id: &str,
+        query: &str,
+    ) -> VaultResult<Vec<ArchiveSearchMatch>> {
+        self.vault_service.get_vault(vault_id).await?;
+        self.archive_service.search_archives(vault_id, query)
+    }
+
+    /// Search archived files across the vaults `scope` covers
+    pub async fn search_files(
+        &self,
+        query: &str,
+        scope: &FileSearchScope,
+    ) -> VaultResult<FileSearchResults> {
+        if let FileSearchScope::Selected(vault_ids) = scope {
+            for vault_id in vault_ids {
+                self.vault_service
+                    .get_vault(vault_id)
+                    .await
+                    .map_err(|_| VaultError::NotFound(vault_id.clone()))?;
+            }
+        }
+        self.file_search_service.search(query, scope).await
+    }
+
+    /// Replace an archive's comment (index and external manifest only)
+    pub async fn update_archive_comment(
+        &self,
+        vault_id: &str,
+        archive_id: &str,
+        comment: Option<&str>,
+    ) -> VaultResult<ArchiveIndexEntry> {
+        let vault = self.vault_service.get_vault(vault_id).await?;
+        self.archive_service.update_archive_comment(
+            vault_id,
+            &vault.vault.sanitized_name,
+            archive_id,
+            comment,
+        )
+    }
+
+    /// A vault's archives with immutability, OS protection, and retention state
+    pub async fn list_archives(&self, vault_id: &str) -> VaultResult<Vec<ArchiveListing>> {
+        self.vault_service.get_vault(vault_id).await?;
+        let mut listings = self.archive_service.list_archive_listings(vault_id)?;
+
+        // The listing stays usable if the policy can't be evaluated
+        match self.retention_service.evaluate(vault_id) {
+            Ok(evaluation) => {
+                for listing in &mut listings {
+                    listing.retention = evaluation
+                        .archives
+                        .iter()
+                        .find(|archive| archive.archive_id == listing.entry.archive_id)
+                        .map(|archive| archive.decision);
+                }
+            }
+            Err(e) => warn!(vault_id, "Failed to evaluate retention: {}", e),
+        }
+        Ok(listings)
+    }
+
+    /// A vault's statistics over `range`, downsampled to at most `max_points`
+    pub async fn get_statistics_history(
+        &self,
+        vault_id: &str,
+        range: StatisticsRange,
+        max_points: Option<usize>,
+    ) -> VaultResult<VaultStatisticsHistory> {
+        self.vault_service.get_vault(vault_id).await?;
+        self.statistics_history_service
+            .history(vault_id, range, max_points)
+    }
+
+    /// Set a vault's retention policy, or clear it with `None`
+    pub async fn set_retention_policy(
+        &self,
+        vault_id: &str,
+        policy: Option<RetentionPolicy>,
+    ) -> VaultResult<Option<RetentionPolicy>> {
+        self.vault_service.get_vault(vault_id).await?;
+        self.retention_service.set_policy(vault_id, policy)
+    }
+
+    /// Classify a vault's archives as retained or eligible for pruning
+    pub async fn evaluate_retention(&self, vault_id: &str) -> VaultResult<RetentionEvaluation> {
+        self.vault_service.get_vault(vault_id).await?;
+        self.retention_service.evaluate(vault_id)
+    }
+
+    /// Prune the given archives, or with `policy` exactly the eligible set
+    pub async fn prune_archives(
+        &self,
+        vault_id: &str,
+        archive_ids: &[String],
+        policy: bool,
+        confirmation: Option<&str>,
+    ) -> VaultResult<ArchivePruneReport> {
+        self.vault_service.get_vault(vault_id).await?;
+        if !policy {
+            return self
+                .archive_service
+                .prune_archives(vault_id, archive_ids, confirmation);
+        }
+        if !archive_ids.is_empty() {
+            return Err(VaultError::InvalidOperation(
+                "Pass either archive IDs or policy, not both".to_string(),
+            ));
+        }
+        self.retention_service
+            .prune_eligible(vault_id, confirmation)
+    }
+
+    /// Mark an archive immutable, or clear the flag with a confirmation
+    pub async fn set_archive_immutable(
+        &self,
+        vault_id: &str,
+        archive_id: &str,
+        immutable: bool,
+        confirmation: Option<&str>,
+    ) -> VaultResult<ArchiveListing> {
+        self.vault_service.get_vault(vault_id).await?;
+        self.archive_service
+            .set_archive_immutable(vault_id, archive_id, immutable, confirmation)
+    }
+
+    /// Rebuild an archive's damaged blocks from its parity sidecar
+    pub async fn repair_archive(
+        &self,
+        vault_id: &str,
+        archive_id: &str,
+    ) -> VaultResult<ArchiveRepairReport> {
+        self.vault_service.get_vault(vault_id).await?;
+        self.repair_service.repair_archive(vault_id, archive_id)
+    }
+
+    /// Replace a few small files of an archive with a patch layered on it
+    pub async fn patch_archive(
+        &self,
+        vault_id: &str,
+        archive_id: &str,
+        replacements: &[PatchReplacement],
+    ) -> VaultResult<ArchivePatchEntry> {
+        let vault = self.vault_service.get_vault(vault_id).await?;
+        self.patch_service.patch_archive(
+            vault_id,
+            &vault.vault.sanitized_name,
+            archive_id,
+            replacements,
+        )
+    }
+
+    /// Add a structured item to a vault
+    pub async fn add_vault_item(
+        &self,
+        vault_id: &str,
+        input: VaultItemInput,
+    ) -> VaultResult<VaultItem> {
+        self.item_service.add_item(vault_id, input).await
+    }
+
+    /// Replace a vault item's editable fields
+    pub async fn update_vault_item(
+        &self,
+        vault_id: &str,
+        item_id: &str,
+        input: VaultItemInput,
+    ) -> VaultResult<VaultItem> {
+        self.item_service
+            .update_item(vault_id, item_id, input)
+            .await
+    }
+
+    /// Remove a vault item
+    pub async fn remove_vault_item(&self, vault_id: &str, item_id: &str) -> VaultResult<VaultItem> {
+        self.item_service.remove_item(vault_id, item_id).await
+    }
+
+    /// List a vault's items with any links that no longer resolve
+    pub async fn list_vault_items(&self, vault_id: &str) -> VaultResult<Vec<VaultItemView>> {
+        self.item_service.list_items(vault_id).await
+    }
+
+    /// First-run checklist plus milestones completed since the last check
+    pub async fn get_onboarding_status(
+        &self,
+    ) -> VaultResult<(OnboardingStatus, Vec<MilestoneStatus>)> {
+        self.onboarding_service.get_status().await
+    }
+
+    /// Record this app start; returns the changes since the previous version
+    /// on the first start after an upgrade
+    pub fn record_app_start(&self) -> VaultResult<Option<CompatibilityReport>> {
+        self.compatibility_service.record_start()
+    }
+
+    /// Record the interface's `frontend_ready` handshake, comparing its
+    /// bindings hash with `expected_bindings_hash`
+    pub fn record_frontend_ready(
+        &self,
+        ui_version: &str,
+        bindings_hash: &str,
+        expected_bindings_hash: Option<&str>,
+    ) -> VaultResult<FrontendHandshake> {
+        self.launch_watchdog.record_handshake(
+            ui_version,
+            bindings_hash,
+            expected_bindings_hash,
+            Utc::now(),
+        )
+    }
+
+    /// Write the latest failed launch's diagnostic record into `destination_dir`
+    pub fn export_launch_diagnostics(&self, destination_dir: &Path) -> VaultResult<PathBuf> {
+        self.launch_watchdog.export_failed_launch(destination_dir)
+    }
+
+    /// Refuse a copy of `kind` unless copying is on and, for recovery codes,
+    /// confirmed; returns the settings the copy runs under
+    pub fn check_clipboard_copy(
+        &self,
+        kind: ClipboardKind,
+        confirmation: Option<&str>,
+    ) -> VaultResult<ClipboardSettings> {
+        let settings = AppConfig::load()
+            .map_err(|e| VaultError::StorageError(e.to_string()))?
+            .clipboard;
+        ClipboardGuard::check_allowed(&settings, kind, confirmation)?;
+        Ok(settings)
+    }
+
+    /// Put a resolved value on the clipboard; returns the copy's generation
+    /// for `clear_clipboard_if_unchanged`
+    pub fn copy_to_clipboard(
+        &self,
+        backend: &dyn ClipboardBackend,
+        copy: ClipboardCopy<'_>,
+        clear_after: DurationMs,
+    ) -> VaultResult<u64> {
+        self.clipboard_guard
+            .copy(backend, copy, clear_after, Utc::now())
+    }
+
+    /// Clear the clipboard if it still holds copy `generation`
+    pub fn clear_clipboard_if_unchanged(
+        &self,
+        backend: &dyn ClipboardBackend,
+        generation: u64,
+    ) -> VaultResult<bool> {
+        self.clipboard_guard.clear_if_unchanged(backend, generation)
+    }
+
+    /// Clipboard settings and the copy waiting to be cleared, if any
+    pub fn get_clipboard_guard_status(&self) -> VaultResult<ClipboardGuardStatus> {
+        let settings = AppConfig::load()
+            .map_err(|e| VaultError::StorageError(e.to_string()))?
+            .clipboard;
+        Ok(self.clipboard_guard.status(settings, Utc::now()))
+    }
+
+    /// Location of one of a vault's archives on this device
+    pub fn get_archive_path(&self, vault_id: &str, archive_id: &str) -> VaultResult<PathBuf> {
+        self.archive_service.archive_path(vault_id, archive_id)
+    }
+
+    /// Vault-format changes after `since_version`, defaulting to the version
+    /// that ran before this one
+    pub fn get_compatibility_changes(
+        &self,
+        since_version: Option<&str>,
+    ) -> VaultResult<CompatibilityReport> {
+        self.compatibility_service.get_changes(since_version)
+    }
+
+    /// Run maintenance tasks across vaults, reporting progress under `operation_id`
+    ///
+    /// Unknown vault IDs are rejected up front; once the run starts, a failing
+    /// task is recorded in its cell and the rest still run.
+    pub async fn run_maintenance(
+        &self,
+        operation_id: &str,
+        scope: MaintenanceScope,
+        tasks: Vec<MaintenanceTask>,
+    ) -> VaultResult<MaintenanceReport> {
+        let mut unique_tasks: Vec<MaintenanceTask> = Vec::new();
+        for task in tasks {
+            if !unique_tasks.contains(&task) {
+                unique_tasks.push(task);
+            }
+        }
+        if unique_tasks.is_empty() {
+            return Err(VaultError::InvalidOperation(
+                "Select at least one maintenance task".to_string(),
+            ));
+        }
+
+        let vaults = match scope {
+            MaintenanceScope::AllVaults => self.vault_service.list_vault_metadata().await?,
+            MaintenanceScope::Vaults(vault_ids) => {
+                let mut vaults: Vec<VaultMetadata> = Vec::new();
+                for vault_id in vault_ids {
+                    if vaults.iter().all(|v| v.vault_id() != vault_id) {
+                        vaults.push(self.vault_service.get_vault(&vault_id).await?);
+                    }
+                }
+                vaults
+            }
+        };
+
+        let targets = vaults
+            .iter()
+            .map(MaintenanceTarget::for_vault)
+            .collect::<VaultResult<Vec<_>>>()?;
+
+        self.maintenance_service
+            .run_maintenance(operation_id, targets, unique_tasks)
+            .await
+    }
+
+    /// Compare a local directory against a vault's latest file list
+    pub async fn compare_vault_to_directory(
+        &self,
+        vault_id: &str,
+        archive_id: Option<&str>,
+        directory: &Path,
+        exclude_patterns: &[String],
+        operation_id: &str,
+    ) -> VaultResult<DirectoryComparison> {
+        self.comparison_service
+            .compare(
+                vault_id,
+                archive_id,
+                directory,
+                exclude_patterns,
+                operation_id,
+            )
+            .await
+    }
+
+    /// Export a vault's file listing as CSV or JSON
+    pub async fn export_inventory(
+        &self,
+        vault_id: &str,
+        archive_id: Option<&str>,
+        format: InventoryFormat,
+        output_path: &Path,
+        include_hashes: bool,
+    ) -> VaultResult<InventoryExportResult> {
+        self.inventory_service
+            .export(vault_id, archive_id, format, output_path, include_hashes)
+            .await
+    }
+
+    /// Metadata restore points, newest first
+    pub fn list_metadata_snapshots(&self) -> VaultResult<Vec<MetadataSnapshotSummary>> {
+        self.snapshot_service.list()
+    }
+
+    /// Changes to keys, vaults and archives since a restore point
+    pub fn diff_metadata_snapshot(&self, snapshot_id: &str) -> VaultResult<MetadataSnapshotDiff> {
+        self.snapshot_service.diff(snapshot_id)
+    }
+
+    /// Restore the registry, manifests and archive index from a restore point
+    pub fn restore_metadata_snapshot(
+        &self,
+        snapshot_id: &str,
+        confirmation: Option<&str>,
+    ) -> VaultResult<MetadataRestoreResult> {
+        self.snapshot_service.restore(snapshot_id, confirmation)
+    }
+
+    /// Quarantine a vault's archives left incomplete by an interrupted run
+    pub async fn scan_for_incomplete_archives(
+        &self,
+        vault_id: &str,
+    ) -> VaultResult<IncompleteArchiveReport> {
+        let vault = self.vault_service.get_vault(vault_id).await?;
+        self.quarantine_service.scan_vault(&vault)
+    }
+
+    /// Delete a vault's quarantined files older than `older_than_days`
+    pub async fn purge_quarantine(
+        &self,
+        vault_id: &str,
+        older_than_days: u32,
+    ) -> VaultResult<QuarantinePurgeReport> {
+        let vault = self.vault_service.get_vault(vault_id).await?;
+        self.quarantine_service.purge(&vault, older_than_days)
+    }
+
+    /// Space used by each category of app data, and what could be purged
+    pub fn get_storage_usage(&self) -> VaultResult<StorageUsageReport> {
+        self.storage_quota_service.usage()
+    }
+
+    /// Whether each directory holding keys or plaintext is kept out of the
+    /// search index
+    pub fn get_indexing_protection_status(
+        &self,
+        session_dirs: &[PathBuf],
+    ) -> VaultResult<Vec<SensitiveDirectoryStatus>> {
+        self.storage_quota_service.indexing_protection(session_dirs)
+    }
+
+    /// Purge eligible app data from categories over their quota
+    pub fn enforce_storage_quotas(&self) -> VaultResult<StorageCleanupReport> {
+        self.storage_quota_service.enforce_quotas()
+    }
+
+    /// Report from the most recent maintenance run on this device
+    pub fn get_last_maintenance_report(&self) -> VaultResult<Option<MaintenanceReport>> {
+        self.maintenance_service.get_last_report()
+    }
+
+    /// List all vaults in the user's order, favorites first
     pub async fn list_vaults(&self) -> VaultResult<Vec<VaultSummary>> {
-        self.vault_service.list_vaults().await
+        let mut vaults = self.vault_service.list_vaults().await?;
+        self.order_service.apply(&mut vaults)?;
+        self.conflict_service.apply(&mut vaults);
+        Ok(vaults)
+    }
+
+    /// Set the vault list order; `ordered_ids` must name every vault once
+    pub async fn reorder_vaults(&self, ordered_ids: &[String]) -> VaultResult<()> {
+        let vaults = self.vault_service.list_vaults().await?;
+        self.order_service.reorder(&vaults, ordered_ids)
+    }
+
+    /// Pin a vault to the top of the list or unpin it
+    pub async fn set_vault_favorite(&self, vault_id: &str, favorite: bool) -> VaultResult<()> {
+        let vaults = self.vault_service.list_vaults().await?;
+        self.order_service.set_favorite(&vaults, vault_id, favorite)
+    }
+
+    /// This profile's shared vault store and who is changing it
+    pub fn get_shared_store_status(&self) -> VaultResult<SharedStoreStatus> {
+        self.shared_store_service.status()
+    }
+
+    /// Keep new vaults in a store shared with other accounts, or stop sharing
+    pub fn set_shared_store(&self, store_dir: Option<&Path>) -> VaultResult<SharedStoreStatus> {
+        self.shared_store_service.set_store(store_dir)
+    }
+
+    /// Clear the shared store's lock when its holder is gone
+    pub fn take_over_shared_store(&self) -> VaultResult<SharedStoreStatus> {
+        self.shared_store_service.take_over()
+    }
+
+    /// What quitting now would interrupt
+    pub fn get_shutdown_status(&self) -> ShutdownCheck {
+        self.shutdown_service.check()
+    }
+
+    /// Cancel running operations, wait (bounded) for their cleanup and
+    /// record them per vault, then allow the app to exit; blocks
+    pub fn force_quit_and_abort(&self) -> ForcedQuitReport {
+        self.shutdown_service.force_quit(FORCED_QUIT_TIMEOUT)
+    }
+
+    /// Flush what the app wrote to a removable volume and say whether it
+    /// can be ejected; blocks
+    pub fn prepare_eject(&self, volume: &str) -> EjectPreparation {
+        self.eject_service.prepare_eject(volume)
+    }
+
+    /// Settle a vault changed on two devices at once, unblocking its writes
+    pub fn resolve_vault_conflict(
+        &self,
+        vault_id: &str,
+        strategy: ConflictStrategy,
+    ) -> VaultResult<ConflictResolution> {
+        self.conflict_service.resolve(vault_id, strategy)
     }
 
     /// Get vault metadata by ID
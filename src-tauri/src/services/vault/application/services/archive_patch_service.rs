@@ -0,0 +1,721 @@
+//! Archive Patch Service
+//!
+//! Replaces a few small files of an archive without re-encrypting it. The
+//! replacement files go into a patch payload of their own, encrypted to the
+//! base archive's recipients and written beside it, and the patch is
+//! recorded in the archive index. Decryption lays a base archive's patches
+//! over it, newest first, and checks every file against the digest of the
+//! layer it came from.
+//!
+//! Only a vault's latest archive can be patched: the local manifest holds
+//! only its file list, which supplies the digests a patch supersedes. There
+//! is no consolidation yet; encrypting the vault again writes a new full
+//! archive from the source, which no longer has the patches.
+
+use crate::prelude::*;
+use crate::services::crypto::infrastructure as crypto;
+use crate::services::file::infrastructure::file_operations::{
+    HashAlgorithm, RestoreFilter, calculate_file_hash_with,
+};
+use crate::services::shared::infrastructure::get_vaults_directory;
+use crate::services::shared::infrastructure::io::atomic_write_sync;
+use crate::services::vault::application::services::VaultMetadataService;
+use crate::services::vault::domain::models::{
+    ArchiveIndexEntry, ArchivePatchEntry, PATCH_MANIFEST_ENTRY, PATCH_MANIFEST_SCHEMA,
+    PatchManifest, PatchReplacement, PatchedEntry, check_patch_size, patch_file_name,
+    resolve_overlay,
+};
+use crate::services::vault::domain::{VaultError, VaultResult};
+use crate::services::vault::infrastructure::persistence::{
+    AppConfig, ArchiveIndex, VaultMetadata, snapshot_before,
+};
+use crate::services::vault::infrastructure::vault_repository::storage_error;
+use crate::types::ByteSize;
+use chrono::{DateTime, Utc};
+use flate2::Compression;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
+use std::io::Read;
+use std::path::{Component, Path, PathBuf};
+use zeroize::Zeroizing;
+
+/// A patch ready to be encrypted: its manifest and the replacement files,
+/// in the manifest's order
+#[derive(Debug)]
+pub struct PreparedPatch {
+    pub manifest: PatchManifest,
+    pub contents: Vec<Zeroizing<Vec<u8>>>,
+}
+
+/// A decrypted patch: its manifest and files by archived path
+#[derive(Debug)]
+pub struct PatchLayer {
+    pub manifest: PatchManifest,
+    pub files: HashMap<String, Zeroizing<Vec<u8>>>,
+}
+
+/// Service for archive patches
+#[derive(Debug)]
+pub struct ArchivePatchService {
+    metadata_service: VaultMetadataService,
+}
+
+impl ArchivePatchService {
+    pub fn new() -> Self {
+        Self {
+            metadata_service: VaultMetadataService::new(),
+        }
+    }
+
+    /// Write a patch replacing files of an archive, and record it
+    ///
+    /// `sanitized_name` locates the vault's external manifest.
+    pub fn patch_archive(
+        &self,
+        vault_id: &str,
+        sanitized_name: &str,
+        archive_id: &str,
+        replacements: &[PatchReplacement],
+    ) -> VaultResult<ArchivePatchEntry> {
+        let manifest = self
+            .metadata_service
+            .load_saved(sanitized_name)
+            .map_err(|e| VaultError::StorageError(e.to_string()))?
+            .ok_or_else(|| {
+                VaultError::InvalidOperation(format!(
+                    "Vault '{}' hasn't been encrypted yet",
+                    vault_id
+                ))
+            })?;
+        let cap = AppConfig::load()
+            .map_err(|e| VaultError::StorageError(e.to_string()))?
+            .patch_size_cap;
+        let mut index =
+            ArchiveIndex::load().map_err(|e| VaultError::StorageError(e.to_string()))?;
+
+        let base = Self::patch_base(&index, &manifest, archive_id)?.clone();
+        let earlier: Vec<PatchManifest> = index
+            .patches_of(vault_id, archive_id)
+            .map(|patch| patch.manifest.clone())
+            .collect();
+        let prepared = Self::prepare(&manifest, &base, &earlier, replacements, cap, Utc::now())?;
+        let payload = Self::build_payload(&prepared)?;
+
+        let recipients: Vec<crypto::PublicKey> = if base.recipients.is_empty() {
+            manifest
+                .get_age_recipients()
+                .into_iter()
+                .chain(manifest.contacts().iter().map(|c| c.public_key.clone()))
+                .map(crypto::PublicKey::from)
+                .collect()
+        } else {
+            base.recipients
+                .iter()
+                .cloned()
+                .map(crypto::PublicKey::from)
+                .collect()
+        };
+        let encrypted = crypto::encrypt_data_multi_recipient(&payload, &recipients)
+            .map_err(|e| VaultError::OperationFailed(format!("Patch encryption failed: {}", e)))?;
+
+        let patch_name = patch_file_name(
+            &base.archive_name,
+            prepared.manifest.sequence,
+            &prepared.manifest.patch_id,
+        );
+        let vaults_dir =
+            get_vaults_directory().map_err(|e| VaultError::StorageError(e.to_string()))?;
+        atomic_write_sync(&vaults_dir.join(&patch_name), &encrypted).map_err(|e| {
+            VaultError::StorageError(format!("Failed to write patch '{}': {}", patch_name, e))
+        })?;
+
+        let entry = ArchivePatchEntry {
+            patch_name,
+            archive_sha256: hex::encode(Sha256::digest(&encrypted)),
+            manifest: prepared.manifest,
+        };
+        index.record_patch(entry.clone());
+        snapshot_before("patch_archive");
+        index.save().map_err(storage_error)?;
+
+        info!(
+            vault_id,
+            archive_id,
+            patch_id = %entry.manifest.patch_id,
+            sequence = entry.manifest.sequence,
+            file_count = entry.manifest.entries.len(),
+            size = entry.manifest.total_size().bytes(),
+            "Recorded archive patch"
+        );
+        Ok(entry)
+    }
+
+    /// The archive to patch, which must be the vault's latest and still on disk
+    pub fn patch_base<'a>(
+        index: &'a ArchiveIndex,
+        manifest: &VaultMetadata,
+        archive_id: &str,
+    ) -> VaultResult<&'a ArchiveIndexEntry> {
+        let entry = index
+            .entries(manifest.vault_id())
+            .iter()
+            .find(|entry| entry.archive_id == archive_id)
+            .ok_or_else(|| {
+                VaultError::InvalidOperation(format!("Archive '{}' not found", archive_id))
+            })?;
+        let revision = manifest.encryption_revision();
+        if entry.encryption_revision != revision {
+            return Err(VaultError::InvalidOperation(format!(
+                "Archive '{}' is revision {}, but only the latest archive's file list \
+                 (revision {}) is kept locally",
+                entry.archive_name, entry.encryption_revision, revision
+            )));
+        }
+        let current = index
+            .current_entry(&entry.archive_name)
+            .is_some_and(|current| current.archive_id == entry.archive_id);
+        if !current {
+            return Err(VaultError::InvalidOperation(format!(
+                "Archive '{}' has been replaced by a later encryption",
+                archive_id
+            )));
+        }
+        Ok(entry)
+    }
+
+    /// Read the replacement files and describe them in a patch manifest
+    ///
+    /// Each path must be a file of `base` as `manifest` lists it. The digest
+    /// superseded is the newest of `earlier` patches', else the manifest's.
+    pub fn prepare(
+        manifest: &VaultMetadata,
+        base: &ArchiveIndexEntry,
+        earlier: &[PatchManifest],
+        replacements: &[PatchReplacement],
+        cap: ByteSize,
+        now: DateTime<Utc>,
+    ) -> VaultResult<PreparedPatch> {
+        if replacements.is_empty() {
+            return Err(VaultError::InvalidOperation(
+                "A patch needs at least one replacement file".to_string(),
+            ));
+        }
+
+        let mut seen = HashSet::new();
+        let mut sizes = Vec::with_capacity(replacements.len());
+        for replacement in replacements {
+            if !seen.insert(replacement.path.as_str()) {
+                return Err(VaultError::InvalidOperation(format!(
+                    "'{}' is replaced more than once",
+                    replacement.path
+                )));
+            }
+            let metadata = std::fs::metadata(&replacement.source_file)
+                .ok()
+                .filter(|metadata| metadata.is_file())
+                .ok_or_else(|| {
+                    VaultError::InvalidOperation(format!(
+                        "Replacement '{}' isn't a readable file",
+                        replacement.source_file
+                    ))
+                })?;
+            sizes.push(metadata.len());
+        }
+        check_patch_size(ByteSize(sizes.iter().sum()), cap)?;
+
+        let overlay = resolve_overlay(earlier);
+        let mut entries = Vec::with_capacity(replacements.len());
+        let mut contents = Vec::with_capacity(replacements.len());
+        for replacement in replacements {
+            let file = manifest
+                .content
+                .files
+                .iter()
+                .find(|file| file.path == replacement.path)
+                .ok_or_else(|| {
+                    VaultError::InvalidOperation(format!(
+                        "'{}' isn't a file of archive '{}'",
+                        replacement.path, base.archive_name
+                    ))
+                })?;
+            if file.lossy_name {
+                return Err(VaultError::InvalidOperation(format!(
+                    "'{}' has a name that can't be patched; encrypt the vault again instead",
+                    replacement.path
+                )));
+            }
+
+            let content = Zeroizing::new(std::fs::read(&replacement.source_file).map_err(|e| {
+                VaultError::StorageError(format!(
+                    "Failed to read '{}': {}",
+                    replacement.source_file, e
+                ))
+            })?);
+            let superseded_sha256 = overlay
+                .get(replacement.path.as_str())
+                .map_or(&file.sha256, |(_, patched)| &patched.sha256)
+                .clone();
+            entries.push(PatchedEntry {
+                path: replacement.path.clone(),
+                archived_path: archive_path_string(&manifest.archived_file_path(file)),
+                size: content.len() as u64,
+                sha256: hex::encode(Sha256::digest(&*content)),
+                superseded_sha256,
+            });
+            contents.push(content);
+        }
+        // Checked again on what was read, in case a file grew meanwhile
+        check_patch_size(ByteSize(entries.iter().map(|entry| entry.size).sum()), cap)?;
+
+        let sequence = earlier
+            .iter()
+            .map(|patch| patch.sequence)
+            .max()
+            .unwrap_or(0)
+            + 1;
+        Ok(PreparedPatch {
+            manifest: PatchManifest {
+                schema: PATCH_MANIFEST_SCHEMA.to_string(),
+                patch_id: uuid::Uuid::new_v4().to_string(),
+                vault_id: manifest.vault_id().to_string(),
+                base_archive_id: base.archive_id.clone(),
+                base_revision: base.encryption_revision,
+                sequence,
+                created_at: now,
+                entries,
+            },
+            contents,
+        })
+    }
+
+    /// TAR.GZ payload of a patch: the replacement files at their archived
+    /// paths, then the patch manifest
+    pub fn build_payload(prepared: &PreparedPatch) -> VaultResult<Vec<u8>> {
+        let failed = |e: std::io::Error| {
+            VaultError::OperationFailed(format!("Failed to build patch payload: {}", e))
+        };
+        let manifest_json = serde_json::to_vec_pretty(&prepared.manifest).map_err(|e| {
+            VaultError::OperationFailed(format!("Failed to serialize patch manifest: {}", e))
+        })?;
+
+        let mut builder = tar::Builder::new(GzEncoder::new(Vec::new(), Compression::default()));
+        let files = prepared
+            .manifest
+            .entries
+            .iter()
+            .zip(&prepared.contents)
+            .map(|(entry, content)| (entry.archived_path.as_str(), content.as_slice()));
+        for (path, data) in files.chain([(PATCH_MANIFEST_ENTRY, manifest_json.as_slice())]) {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(data.len() as u64);
+            header.set_mode(0o600);
+            header.set_mtime(prepared.manifest.created_at.timestamp().max(0) as u64);
+            builder
+                .append_data(&mut header, path, data)
+                .map_err(failed)?;
+        }
+        builder
+            .into_inner()
+            .and_then(|encoder| encoder.finish())
+            .map_err(failed)
+    }
+
+    /// Read a decrypted patch payload, checking each file against its digest
+    pub fn read_payload(payload: &[u8]) -> VaultResult<PatchLayer> {
+        let invalid = |message: String| VaultError::InvalidOperation(message);
+        let mut manifest = None;
+        let mut files = HashMap::new();
+
+        let mut archive = tar::Archive::new(GzDecoder::new(payload));
+        let entries = archive
+            .entries()
+            .map_err(|e| invalid(format!("Unreadable patch payload: {}", e)))?;
+        for entry in entries {
+            let mut entry = entry.map_err(|e| invalid(format!("Unreadable patch entry: {}", e)))?;
+            let path = entry
+                .path()
+                .map_err(|e| invalid(format!("Unreadable patch entry name: {}", e)))?
+                .to_string_lossy()
+                .to_string();
+            let mut data = Zeroizing::new(Vec::new());
+            entry
+                .read_to_end(&mut data)
+                .map_err(|e| invalid(format!("Unreadable patch entry '{}': {}", path, e)))?;
+            if path == PATCH_MANIFEST_ENTRY {
+                manifest = Some(
+                    serde_json::from_slice::<PatchManifest>(&data)
+                        .map_err(|e| invalid(format!("Invalid patch manifest: {}", e)))?,
+                );
+            } else {
+                files.insert(path, data);
+            }
+        }
+
+        let manifest = manifest.ok_or_else(|| invalid("Patch has no manifest".to_string()))?;
+        for entry in &manifest.entries {
+            if !is_plain_relative(&entry.archived_path) {
+                return Err(invalid(format!(
+                    "Patch names an unsafe path '{}'",
+                    entry.archived_path
+                )));
+            }
+            let data = files
+                .get(&entry.archived_path)
+                .ok_or_else(|| invalid(format!("Patch is missing '{}'", entry.archived_path)))?;
+            if hex::encode(Sha256::digest(data.as_slice())) != entry.sha256 {
+                return Err(invalid(format!(
+                    "'{}' in patch {} doesn't match its digest",
+                    entry.path, manifest.sequence
+                )));
+            }
+        }
+        Ok(PatchLayer { manifest, files })
+    }
+
+    /// Patches recorded for the archive at `encrypted_file`, oldest first,
+    /// with the path each is expected at beside it
+    ///
+    /// The archive is matched by its manifest's vault and revision and by
+    /// file name. A patch missing from beside the archive is returned too,
+    /// so the caller can say it wasn't applied.
+    pub fn layers_for(
+        manifest: &VaultMetadata,
+        encrypted_file: &Path,
+    ) -> Vec<(PathBuf, ArchivePatchEntry)> {
+        let index = match ArchiveIndex::load() {
+            Ok(index) => index,
+            Err(e) => {
+                warn!(error = %e, "Failed to load archive index, restoring without patches");
+                return Vec::new();
+            }
+        };
+        let Some(file_name) = encrypted_file.file_name() else {
+            return Vec::new();
+        };
+        let dir = encrypted_file.parent().unwrap_or(Path::new(""));
+
+        let mut layers: Vec<(PathBuf, ArchivePatchEntry)> = index
+            .entries(manifest.vault_id())
+            .iter()
+            .filter(|entry| {
+                entry.encryption_revision == manifest.encryption_revision()
+                    && file_name == entry.archive_name.as_str()
+            })
+            .flat_map(|entry| index.patches_of(manifest.vault_id(), &entry.archive_id))
+            .filter(|patch| patch.manifest.base_revision == manifest.encryption_revision())
+            .map(|patch| (dir.join(&patch.patch_name), patch.clone()))
+            .collect();
+        layers.sort_by_key(|(_, patch)| patch.manifest.sequence);
+        layers
+    }
+
+    /// Write the files the patches supply into `root`, newest patch first
+    ///
+    /// Paths `filter` leaves out aren't written. Returns the archived paths
+    /// written with their sizes and SHA-256 digests.
+    pub fn apply_layers(
+        manifest: &VaultMetadata,
+        layers: &[PatchLayer],
+        root: &Path,
+        filter: &RestoreFilter,
+    ) -> VaultResult<Vec<(PathBuf, u64, String)>> {
+        let overlay = resolve_overlay(layers.iter().map(|layer| &layer.manifest));
+        let mut written = Vec::with_capacity(overlay.len());
+        for (path, (patch, entry)) in overlay {
+            let archived = PathBuf::from(&entry.archived_path);
+            let content_type = manifest
+                .content
+                .files
+                .iter()
+                .find(|file| file.path == path)
+                .and_then(|file| file.content_type.as_deref());
+            if !filter.includes(&archived, content_type) {
+                continue;
+            }
+
+            let data = layers
+                .iter()
+                .find(|layer| layer.manifest.patch_id == patch.patch_id)
+                .and_then(|layer| layer.files.get(&entry.archived_path))
+                .ok_or_else(|| {
+                    VaultError::InvalidOperation(format!("Patch is missing '{}'", path))
+                })?;
+            let target = root.join(&archived);
+            if let Some(parent) = target.parent() {
+                std::fs::create_dir_all(parent).map_err(|e| {
+                    VaultError::StorageError(format!("Failed to create '{}': {}", path, e))
+                })?;
+            }
+            std::fs::write(&target, data.as_slice()).map_err(|e| {
+                VaultError::StorageError(format!("Failed to write '{}': {}", path, e))
+            })?;
+            debug!(path, sequence = patch.sequence, "Applied patched file");
+            written.push((archived, entry.size, entry.sha256.clone()));
+        }
+        Ok(written)
+    }
+
+    /// Check each file `manifest` lists under `root` against the digest of
+    /// the layer it comes from: the newest patch naming it, else the base
+    ///
+    /// Files `filter` left out aren't checked. Returns how many were.
+    pub fn verify_layers<'a>(
+        manifest: &VaultMetadata,
+        patches: impl IntoIterator<Item = &'a PatchManifest>,
+        root: &Path,
+        filter: &RestoreFilter,
+    ) -> VaultResult<usize> {
+        let overlay = resolve_overlay(patches);
+        let mut checked = 0;
+        for file in &manifest.content.files {
+            let archived = manifest.archived_file_path(file);
+            if !filter.includes(&archived, file.content_type.as_deref()) {
+                continue;
+            }
+            let path = root.join(&archived);
+            let unreadable =
+                |e| VaultError::InvalidOperation(format!("Can't check '{}': {}", file.path, e));
+            let matches = match overlay.get(file.path.as_str()) {
+                Some((_, patched)) => calculate_file_hash_with(&path, HashAlgorithm::Sha256)
+                    .map_err(unreadable)?
+                    .eq_ignore_ascii_case(&patched.sha256),
+                None => file.verify_file(&path).map_err(unreadable)?,
+            };
+            if !matches {
+                return Err(VaultError::InvalidOperation(format!(
+                    "'{}' doesn't match the digest of the layer it comes from",
+                    file.path
+                )));
+            }
+            checked += 1;
+        }
+        Ok(checked)
+    }
+}
+
+impl Default for ArchivePatchService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Archived path with `/` separators, as stored in TAR entries
+fn archive_path_string(path: &Path) -> String {
+    path.components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Relative, with no parent or root components
+fn is_plain_relative(path: &str) -> bool {
+    !path.is_empty()
+        && Path::new(path)
+            .components()
+            .all(|component| matches!(component, Component::Normal(_)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::file::infrastructure::file_operations::RawPath;
+    use crate::services::shared::infrastructure::DeviceInfo;
+    use crate::services::vault::infrastructure::persistence::metadata::VaultFileEntry;
+    use tempfile::TempDir;
+
+    const DESCRIPTOR: &str = "descriptor.json";
+
+    fn sha256(data: &[u8]) -> String {
+        hex::encode(Sha256::digest(data))
+    }
+
+    /// Manifest of an archive holding `Docs/descriptor.json` and `Docs/notes.txt`
+    fn manifest() -> VaultMetadata {
+        let device = DeviceInfo {
+            machine_id: "machine-001".to_string(),
+            machine_label: "test-machine".to_string(),
+            created_at: Utc::now(),
+            app_version: "1.0.0".to_string(),
+        };
+        let files = [(DESCRIPTOR, b"v0".as_slice()), ("notes.txt", b"notes")]
+            .into_iter()
+            .map(|(path, data)| VaultFileEntry {
+                path: path.to_string(),
+                raw_path: RawPath::from_display(path),
+                lossy_name: false,
+                size: data.len() as u64,
+                sha256: sha256(data),
+                hash_algorithm: HashAlgorithm::Sha256,
+                digest: None,
+                ownership: None,
+                content_type: None,
+                content_type_mismatch: false,
+                transform: None,
+            })
+            .collect();
+        VaultMetadata::new(
+            "vault-001".to_string(),
+            "Family".to_string(),
+            None,
+            "Family".to_string(),
+            &device,
+            Some("Docs".to_string()),
+            vec![],
+            files,
+            2,
+            7,
+        )
+    }
+
+    fn base(manifest: &VaultMetadata) -> ArchiveIndexEntry {
+        ArchiveIndexEntry {
+            archive_id: "a1".to_string(),
+            vault_id: manifest.vault_id().to_string(),
+            archive_name: "Family.age".to_string(),
+            encryption_revision: manifest.encryption_revision(),
+            created_at: Utc::now(),
+            file_count: 2,
+            comment: None,
+            comment_updated_at: None,
+            immutable: false,
+            parity: None,
+            migrated_from: None,
+            prune_marked: false,
+            remapped_from: None,
+            archive_sha256: None,
+            recipients: Vec::new(),
+        }
+    }
+
+    /// Prepare a patch replacing the descriptor with `data`, then read its
+    /// payload back as decryption would
+    fn patch(
+        temp: &TempDir,
+        manifest: &VaultMetadata,
+        earlier: &[PatchManifest],
+        data: &[u8],
+    ) -> PatchLayer {
+        let source = temp.path().join(format!("replacement-{}", earlier.len()));
+        std::fs::write(&source, data).unwrap();
+        let replacements = [PatchReplacement {
+            path: DESCRIPTOR.to_string(),
+            source_file: source.to_string_lossy().to_string(),
+        }];
+        let prepared = ArchivePatchService::prepare(
+            manifest,
+            &base(manifest),
+            earlier,
+            &replacements,
+            ByteSize(1024),
+            Utc::now(),
+        )
+        .unwrap();
+        let payload = ArchivePatchService::build_payload(&prepared).unwrap();
+        ArchivePatchService::read_payload(&payload).unwrap()
+    }
+
+    /// Restore the base archive's files under `root`
+    fn restore_base(root: &Path) {
+        std::fs::create_dir_all(root.join("Docs")).unwrap();
+        std::fs::write(root.join("Docs").join(DESCRIPTOR), b"v0").unwrap();
+        std::fs::write(root.join("Docs/notes.txt"), b"notes").unwrap();
+    }
+
+    #[test]
+    fn test_patch_carries_replacements_and_superseded_digests() {
+        let temp = TempDir::new().unwrap();
+        let manifest = manifest();
+
+        let layer = patch(&temp, &manifest, &[], b"v1");
+
+        assert_eq!(layer.manifest.sequence, 1);
+        assert_eq!(layer.manifest.base_archive_id, "a1");
+        let entry = &layer.manifest.entries[0];
+        assert_eq!(entry.archived_path, "Docs/descriptor.json");
+        assert_eq!(entry.sha256, sha256(b"v1"));
+        assert_eq!(entry.superseded_sha256, sha256(b"v0"));
+        assert_eq!(layer.files[&entry.archived_path].as_slice(), b"v1");
+    }
+
+    #[test]
+    fn test_stacked_patches_overlay_newest_first() {
+        let temp = TempDir::new().unwrap();
+        let manifest = manifest();
+        let first = patch(&temp, &manifest, &[], b"v1");
+        let second = patch(&temp, &manifest, &[first.manifest.clone()], b"v2");
+        assert_eq!(second.manifest.sequence, 2);
+        assert_eq!(second.manifest.entries[0].superseded_sha256, sha256(b"v1"));
+
+        let root = temp.path().join("restored");
+        restore_base(&root);
+        let written = ArchivePatchService::apply_layers(
+            &manifest,
+            &[second, first],
+            &root,
+            &RestoreFilter::default(),
+        )
+        .unwrap();
+
+        assert_eq!(written.len(), 1);
+        let restored = std::fs::read(root.join("Docs").join(DESCRIPTOR)).unwrap();
+        assert_eq!(restored, b"v2");
+    }
+
+    #[test]
+    fn test_verification_follows_each_files_layer() {
+        let temp = TempDir::new().unwrap();
+        let manifest = manifest();
+        let first = patch(&temp, &manifest, &[], b"v1");
+        let second = patch(&temp, &manifest, &[first.manifest.clone()], b"v2");
+        let patches = [first.manifest.clone(), second.manifest.clone()];
+        let root = temp.path().join("restored");
+        restore_base(&root);
+        let all = RestoreFilter::default();
+
+        // The base's descriptor no longer matches once patches exist
+        assert!(ArchivePatchService::verify_layers(&manifest, &patches, &root, &all).is_err());
+
+        ArchivePatchService::apply_layers(&manifest, &[first, second], &root, &all).unwrap();
+        assert_eq!(
+            ArchivePatchService::verify_layers(&manifest, &patches, &root, &all).unwrap(),
+            2
+        );
+        // Without the patches, only the base's digests count
+        assert!(ArchivePatchService::verify_layers(&manifest, [], &root, &all).is_err());
+    }
+
+    #[test]
+    fn test_replacements_over_the_cap_are_refused() {
+        let temp = TempDir::new().unwrap();
+        let manifest = manifest();
+        let source = temp.path().join("large");
+        std::fs::write(&source, vec![0u8; 2048]).unwrap();
+        let replacements = [PatchReplacement {
+            path: DESCRIPTOR.to_string(),
+            source_file: source.to_string_lossy().to_string(),
+        }];
+
+        let result = ArchivePatchService::prepare(
+            &manifest,
+            &base(&manifest),
+            &[],
+            &replacements,
+            ByteSize(1024),
+            Utc::now(),
+        );
+
+        assert!(
+            matches!(result, Err(VaultError::InvalidOperation(msg)) if msg.contains("more than"))
+        );
+    }
+
+    #[test]
+    fn test_unsafe_patch_paths_are_refused() {
+        assert!(is_plain_relative("Docs/descriptor.json"));
+        assert!(!is_plain_relative("../outside"));
+        assert!(!is_plain_relative("/etc/passwd"));
+        assert!(!is_plain_relative(""));
+    }
+}
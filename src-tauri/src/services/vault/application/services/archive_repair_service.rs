@@ -0,0 +1,252 @@
+//! Archive Repair Service
+//!
+//! Rebuilds archive blocks damaged on storage media from the parity sidecar
+//! written at encryption time. The repaired archive is written through the
+//! verified overwrite, so the file on disk is either the damaged original or
+//! the archive exactly as encrypted. Nothing is written when some damage is
+//! beyond what the parity covers.
+
+use crate::prelude::*;
+use crate::services::file::infrastructure::file_operations::ParitySidecar;
+use crate::services::shared::infrastructure::get_vaults_directory;
+use crate::services::shared::infrastructure::io::ArchiveOverwrite;
+use crate::services::vault::application::services::ArchiveService;
+use crate::services::vault::domain::models::{ArchiveRepairReport, RepairOutcome};
+use crate::services::vault::domain::{VaultError, VaultResult};
+use crate::services::vault::infrastructure::persistence::ArchiveIndex;
+use std::path::Path;
+
+/// Service for repairing archives from their parity
+#[derive(Debug, Default)]
+pub struct ArchiveRepairService;
+
+impl ArchiveRepairService {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Check an archive against its parity and rebuild what's damaged
+    pub fn repair_archive(
+        &self,
+        vault_id: &str,
+        archive_id: &str,
+    ) -> VaultResult<ArchiveRepairReport> {
+        let index = ArchiveIndex::load().map_err(|e| VaultError::StorageError(e.to_string()))?;
+        let vaults_dir =
+            get_vaults_directory().map_err(|e| VaultError::StorageError(e.to_string()))?;
+        Self::repair_in(&index, &vaults_dir, vault_id, archive_id)
+    }
+
+    /// Repair an indexed archive stored in `vaults_dir`
+    ///
+    /// Only the current archive can be repaired; older encryptions no longer
+    /// exist on disk. Immutable archives are checked but not written.
+    pub fn repair_in(
+        index: &ArchiveIndex,
+        vaults_dir: &Path,
+        vault_id: &str,
+        archive_id: &str,
+    ) -> VaultResult<ArchiveRepairReport> {
+        let entry = index
+            .entries(vault_id)
+            .iter()
+            .find(|entry| entry.archive_id == archive_id)
+            .ok_or_else(|| {
+                VaultError::InvalidOperation(format!("Archive '{}' not found", archive_id))
+            })?;
+        let current = index
+            .current_entry(&entry.archive_name)
+            .is_some_and(|current| current.archive_id == entry.archive_id);
+        if !current {
+            return Err(VaultError::InvalidOperation(format!(
+                "Archive '{}' has been replaced by a later encryption",
+                archive_id
+            )));
+        }
+        let Some(parity) = &entry.parity else {
+            return Err(VaultError::InvalidOperation(format!(
+                "Archive '{}' was encrypted without parity",
+                entry.archive_name
+            )));
+        };
+
+        let archive_path = vaults_dir.join(&entry.archive_name);
+        let sidecar = ParitySidecar::open(&vaults_dir.join(&parity.file_name))
+            .map_err(|e| VaultError::StorageError(e.to_string()))?
+            .ok_or_else(|| {
+                VaultError::InvalidOperation(format!(
+                    "Parity file '{}' is missing",
+                    parity.file_name
+                ))
+            })?;
+
+        // A missing archive is rebuilt like one damaged end to end
+        let mut archive = match std::fs::read(&archive_path) {
+            Ok(archive) => archive,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => return Err(VaultError::StorageError(e.to_string())),
+        };
+        let check = sidecar
+            .repair(&mut archive)
+            .map_err(|e| VaultError::OperationFailed(format!("Repair failed: {}", e)))?;
+        let report = ArchiveRepairReport::new(archive_id, check);
+
+        match report.outcome {
+            RepairOutcome::Intact => {
+                info!(vault_id, archive_id, "Archive matches its parity");
+                return Ok(report);
+            }
+            RepairOutcome::Unrepairable => {
+                warn!(
+                    vault_id,
+                    archive_id,
+                    damaged_bytes = report.check.damaged_bytes(),
+                    unrepairable_ranges = report.check.unrepairable_ranges.len(),
+                    "Archive damage exceeds its parity"
+                );
+                return Ok(report);
+            }
+            RepairOutcome::Repaired => {}
+        }
+
+        ArchiveService::check_replaceable(index, &entry.archive_name)?;
+        if !report.repaired_ranges.is_empty() {
+            let mut overwrite = ArchiveOverwrite::new();
+            overwrite
+                .stage(&archive_path, &archive)
+                .and_then(|()| overwrite.commit())
+                .map_err(|e| {
+                    VaultError::StorageError(format!("Failed to write repaired archive: {}", e))
+                })?;
+        }
+        if report.parity_blocks_rebuilt > 0 {
+            sidecar.regenerate(&archive_path).map_err(|e| {
+                VaultError::StorageError(format!("Failed to rewrite parity: {}", e))
+            })?;
+        }
+
+        info!(
+            vault_id,
+            archive_id,
+            repaired_bytes = report.check.damaged_bytes(),
+            repaired_ranges = report.repaired_ranges.len(),
+            parity_blocks_rebuilt = report.parity_blocks_rebuilt,
+            "Repaired archive from parity"
+        );
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::file::infrastructure::file_operations::{
+        PARITY_BLOCK_SIZE, generate_parity,
+    };
+    use crate::services::vault::domain::models::ArchiveIndexEntry;
+    use chrono::Utc;
+    use rand::Rng;
+    use tempfile::TempDir;
+
+    /// A 25-block age archive with 10% parity, recorded in a fresh index
+    fn fixture(dir: &Path) -> (ArchiveIndex, Vec<u8>) {
+        let mut payload = vec![0u8; 24 * PARITY_BLOCK_SIZE];
+        rand::thread_rng().fill(&mut payload[..]);
+        let recipient = age::x25519::Identity::generate().to_public();
+        let archive = age::encrypt(&recipient, &payload).unwrap();
+        std::fs::write(dir.join("Family.age"), &archive).unwrap();
+        let parity = generate_parity(&dir.join("Family.age"), 10, &|_, _| {}).unwrap();
+
+        let mut index = ArchiveIndex::default();
+        index.record(ArchiveIndexEntry {
+            archive_id: "a1".to_string(),
+            vault_id: "vault-001".to_string(),
+            archive_name: "Family.age".to_string(),
+            encryption_revision: 1,
+            created_at: Utc::now(),
+            file_count: 1,
+            comment: None,
+            comment_updated_at: None,
+            immutable: false,
+            parity: Some(parity),
+            migrated_from: None,
+            prune_marked: false,
+            remapped_from: None,
+            archive_sha256: None,
+            recipients: Vec::new(),
+        });
+        (index, archive)
+    }
+
+    fn corrupt(dir: &Path, blocks: &[usize]) {
+        let path = dir.join("Family.age");
+        let mut archive = std::fs::read(&path).unwrap();
+        for block in blocks {
+            archive[block * PARITY_BLOCK_SIZE + 100] ^= 0x01;
+        }
+        std::fs::write(&path, archive).unwrap();
+    }
+
+    #[test]
+    fn test_repairs_damaged_header_and_payload() {
+        let temp = TempDir::new().unwrap();
+        let (index, original) = fixture(temp.path());
+        corrupt(temp.path(), &[0, 11, 12]);
+
+        let report =
+            ArchiveRepairService::repair_in(&index, temp.path(), "vault-001", "a1").unwrap();
+        assert_eq!(report.outcome, RepairOutcome::Repaired);
+        let ranges: Vec<(u64, u64)> = report
+            .repaired_ranges
+            .iter()
+            .map(|range| (range.offset.bytes(), range.length.bytes()))
+            .collect();
+        let block = PARITY_BLOCK_SIZE as u64;
+        assert_eq!(ranges, vec![(0, block), (11 * block, 2 * block)]);
+        assert_eq!(
+            std::fs::read(temp.path().join("Family.age")).unwrap(),
+            original
+        );
+
+        let again =
+            ArchiveRepairService::repair_in(&index, temp.path(), "vault-001", "a1").unwrap();
+        assert_eq!(again.outcome, RepairOutcome::Intact);
+    }
+
+    #[test]
+    fn test_damage_beyond_parity_is_left_untouched() {
+        let temp = TempDir::new().unwrap();
+        let (index, _) = fixture(temp.path());
+        corrupt(temp.path(), &[1, 2, 3, 5, 8, 13, 21, 22]);
+        let damaged = std::fs::read(temp.path().join("Family.age")).unwrap();
+
+        let report =
+            ArchiveRepairService::repair_in(&index, temp.path(), "vault-001", "a1").unwrap();
+        assert_eq!(report.outcome, RepairOutcome::Unrepairable);
+        assert!(report.repaired_ranges.is_empty());
+        assert_eq!(report.check.unrepairable_ranges.len(), 5);
+        assert_eq!(
+            std::fs::read(temp.path().join("Family.age")).unwrap(),
+            damaged
+        );
+    }
+
+    #[test]
+    fn test_refuses_archives_without_parity_or_replaced() {
+        let temp = TempDir::new().unwrap();
+        let (mut index, _) = fixture(temp.path());
+        index.find_mut("vault-001", "a1").unwrap().parity = None;
+
+        let error =
+            ArchiveRepairService::repair_in(&index, temp.path(), "vault-001", "a1").unwrap_err();
+        assert!(error.to_string().contains("without parity"), "{error}");
+
+        let mut later = index.entries("vault-001")[0].clone();
+        later.archive_id = "a2".to_string();
+        later.created_at = Utc::now() + chrono::Duration::seconds(1);
+        index.record(later);
+        let error =
+            ArchiveRepairService::repair_in(&index, temp.path(), "vault-001", "a1").unwrap_err();
+        assert!(error.to_string().contains("replaced"), "{error}");
+    }
+}
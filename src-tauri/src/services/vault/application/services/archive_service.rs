@@ -0,0 +1,962 @@
+//! Archive Service
+//!
+//! Records each encryption in the archive index, searches it, and edits
+//! archive comments. Comment edits touch only the indexes and the external
+//! manifest; the encrypted payload is never rewritten. The file search index
+//! follows every change made here.
+//!
+//! Comments are user content: log their length, never their text.
+//!
+//! Archives can be marked immutable. Anything that would write over or
+//! remove an archive file must call `ensure_replaceable` first; the flag is
+//! only cleared with an explicit confirmation.
+//!
+//! Pruning removes entries from the index, also after a typed confirmation.
+//! An archive file goes with them once no remaining entry refers to it,
+//! and the patches layered on a pruned archive always go with it.
+//! Neither the vault's latest archive nor an immutable one can be pruned.
+//! Recording and pruning both add to the vault's statistics history.
+//!
+//! An archive re-encrypted by a parameter migration links back to the one it
+//! replaced, which can be marked eligible for pruning.
+
+use crate::prelude::*;
+use crate::services::file::domain::models::ParityInfo;
+use crate::services::shared::infrastructure::io::{is_os_protected, set_os_protection};
+use crate::services::shared::infrastructure::{
+    ChangeEvent, SecureDeleteService, get_vaults_directory, publish,
+};
+use crate::services::vault::application::services::{
+    FileSearchService, StatisticsHistoryService, VaultMetadataService,
+};
+use crate::services::vault::domain::models::{
+    ArchiveIndexEntry, ArchiveListing, ArchivePatchEntry, ArchivePruneReport, ArchiveSearchMatch,
+    CLEAR_IMMUTABLE_CONFIRMATION, PRUNE_ARCHIVES_CONFIRMATION, PrunedArchive, latest_archive,
+    search_archive_entries, validate_archive_comment,
+};
+use crate::services::vault::domain::{VaultError, VaultResult};
+use crate::services::vault::infrastructure::persistence::{
+    ArchiveIndex, VaultMetadata, snapshot_before,
+};
+use crate::services::vault::infrastructure::vault_repository::storage_error;
+use crate::types::ByteSize;
+use chrono::{DateTime, Utc};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+/// Service for the archive index
+#[derive(Debug)]
+pub struct ArchiveService {
+    metadata_service: VaultMetadataService,
+    file_search: FileSearchService,
+    statistics_history: StatisticsHistoryService,
+    deleter: SecureDeleteService,
+}
+
+impl ArchiveService {
+    pub fn new() -> Self {
+        Self {
+            metadata_service: VaultMetadataService::new(),
+            file_search: FileSearchService::new(),
+            statistics_history: StatisticsHistoryService::new(),
+            deleter: SecureDeleteService::new(),
+        }
+    }
+
+    /// Record a completed encryption, using the manifest's comment
+    ///
+    /// `recipients` are the public keys the archive was encrypted to.
+    pub fn record_archive(
+        &self,
+        manifest: &VaultMetadata,
+        archive_name: &str,
+        parity: Option<ParityInfo>,
+        archive_sha256: Option<String>,
+        recipients: Vec<String>,
+    ) -> VaultResult<ArchiveIndexEntry> {
+        let mut index = load_index()?;
+        let mut entry = Self::record_in(&mut index, manifest, archive_name);
+        if let Some(stored) = index.find_mut(&entry.vault_id, &entry.archive_id) {
+            stored.parity = parity;
+            stored.archive_sha256 = archive_sha256;
+            stored.recipients = recipients;
+            entry = stored.clone();
+        }
+        save_index("record_archive", &index)?;
+        self.file_search.index_archive(manifest, &entry);
+        self.statistics_history.record(&index, &entry.vault_id);
+        publish(ChangeEvent::ArchiveAdded {
+            vault_id: entry.vault_id.clone(),
+            archive_id: entry.archive_id.clone(),
+        });
+        Ok(entry)
+    }
+
+    /// Archives recorded for a vault, oldest first
+    pub fn list_archives(&self, vault_id: &str) -> VaultResult<Vec<ArchiveIndexEntry>> {
+        Ok(load_index()?.entries(vault_id).to_vec())
+    }
+
+    /// Location of one of a vault's archives on this device
+    pub fn archive_path(&self, vault_id: &str, archive_id: &str) -> VaultResult<PathBuf> {
+        let entry = self
+            .list_archives(vault_id)?
+            .into_iter()
+            .find(|entry| entry.archive_id == archive_id)
+            .ok_or_else(|| VaultError::NotFound(format!("archive {}", archive_id)))?;
+        Ok(vaults_dir()?.join(entry.archive_name))
+    }
+
+    /// A vault's archives with their protection state, oldest first
+    pub fn list_archive_listings(&self, vault_id: &str) -> VaultResult<Vec<ArchiveListing>> {
+        let index = load_index()?;
+        let vaults_dir = vaults_dir()?;
+        Ok(Self::listings(&index, vault_id, |name| {
+            is_os_protected(&vaults_dir.join(name))
+        }))
+    }
+
+    /// Mark an archive immutable, or clear the flag with `confirmation`
+    ///
+    /// OS-level protection follows the flag where the platform allows it.
+    pub fn set_archive_immutable(
+        &self,
+        vault_id: &str,
+        archive_id: &str,
+        immutable: bool,
+        confirmation: Option<&str>,
+    ) -> VaultResult<ArchiveListing> {
+        let mut index = load_index()?;
+        let (entry, current) =
+            Self::apply_immutable(&mut index, vault_id, archive_id, immutable, confirmation)?;
+        let path = vaults_dir()?.join(&entry.archive_name);
+
+        // Lift OS protection before saving, so a failure leaves the archive fully protected
+        if !immutable && current && set_os_protection(&path, false) {
+            return Err(VaultError::OperationFailed(format!(
+                "Couldn't remove OS protection from '{}'",
+                entry.archive_name
+            )));
+        }
+        save_index("set_archive_immutable", &index)?;
+        let os_protected = immutable && current && set_os_protection(&path, true);
+
+        Ok(ArchiveListing {
+            entry,
+            current,
+            os_protected,
+            retention: None,
+        })
+    }
+
+    /// Link a migrated archive to the archive it was re-encrypted from
+    pub fn link_migration(
+        &self,
+        vault_id: &str,
+        archive_id: &str,
+        migrated_from: &str,
+        prune_original: bool,
+    ) -> VaultResult<ArchiveIndexEntry> {
+        let mut index = load_index()?;
+        let entry = Self::apply_migration_link(
+            &mut index,
+            vault_id,
+            archive_id,
+            migrated_from,
+            prune_original,
+        )?;
+        save_index("link_migration", &index)?;
+        Ok(entry)
+    }
+
+    /// Link a remapped archive to the archive it was rewritten from
+    pub fn link_remap(
+        &self,
+        vault_id: &str,
+        archive_id: &str,
+        remapped_from: &str,
+    ) -> VaultResult<ArchiveIndexEntry> {
+        let mut index = load_index()?;
+        let entry = Self::apply_remap_link(&mut index, vault_id, archive_id, remapped_from)?;
+        save_index("link_remap", &index)?;
+        Ok(entry)
+    }
+
+    /// Remove archives from a vault's index once `confirmation` is typed
+    ///
+    /// Archive files no remaining entry refers to are deleted with their
+    /// parity sidecars.
+    pub fn prune_archives(
+        &self,
+        vault_id: &str,
+        archive_ids: &[String],
+        confirmation: Option<&str>,
+    ) -> VaultResult<ArchivePruneReport> {
+        if confirmation != Some(PRUNE_ARCHIVES_CONFIRMATION) {
+            return Err(VaultError::InvalidOperation(format!(
+                "Type '{}' to confirm pruning archives",
+                PRUNE_ARCHIVES_CONFIRMATION
+            )));
+        }
+
+        let mut index = load_index()?;
+        let removed = Self::apply_prune(&mut index, vault_id, archive_ids)?;
+        if removed.is_empty() {
+            return Ok(ArchivePruneReport::default());
+        }
+        let patches: Vec<ArchivePatchEntry> = removed
+            .iter()
+            .flat_map(|(entry, _)| index.remove_patches_of(vault_id, &entry.archive_id))
+            .collect();
+        save_index("prune_archives", &index)?;
+        let removed_ids: Vec<String> = removed
+            .iter()
+            .map(|(entry, _)| entry.archive_id.clone())
+            .collect();
+        self.file_search.remove_archives(vault_id, &removed_ids);
+
+        let vaults_dir = vaults_dir()?;
+        let mut report = ArchivePruneReport::default();
+        for (entry, orphaned) in removed {
+            let file_deleted =
+                orphaned && self.delete_archive_files(&vaults_dir, &entry, &mut report.freed);
+            report.pruned.push(PrunedArchive {
+                archive_id: entry.archive_id,
+                archive_name: entry.archive_name,
+                file_deleted,
+            });
+        }
+        for patch in patches {
+            let path = vaults_dir.join(&patch.patch_name);
+            if !path.exists() {
+                continue;
+            }
+            let size = file_size(&path);
+            match self.deleter.delete_file(&path) {
+                Ok(_) => report.freed += ByteSize(size),
+                Err(e) => warn!(
+                    patch_id = %patch.manifest.patch_id,
+                    error = %e,
+                    "Failed to delete patch of pruned archive"
+                ),
+            }
+        }
+        self.statistics_history.record(&index, vault_id);
+
+        info!(
+            vault_id,
+            pruned = report.pruned.len(),
+            freed = report.freed.bytes(),
+            "Pruned archives"
+        );
+        Ok(report)
+    }
+
+    /// Space pruning `archive_ids` would free on disk
+    pub fn prune_reclaimable(
+        &self,
+        vault_id: &str,
+        archive_ids: &[String],
+    ) -> VaultResult<ByteSize> {
+        let index = load_index()?;
+        let vaults_dir = vaults_dir()?;
+        let patches = archive_ids
+            .iter()
+            .flat_map(|archive_id| index.patches_of(vault_id, archive_id))
+            .map(|patch| vaults_dir.join(&patch.patch_name));
+        Ok(Self::orphaned_by_prune(&index, vault_id, archive_ids)
+            .into_iter()
+            .flat_map(|entry| archive_files(&vaults_dir, entry))
+            .chain(patches)
+            .map(|path| ByteSize(file_size(&path)))
+            .sum())
+    }
+
+    /// The archive whose file list the manifest holds
+    ///
+    /// A requested archive must be the latest one; older file lists aren't kept.
+    pub fn resolve_latest_archive(
+        &self,
+        metadata: &VaultMetadata,
+        archive_id: Option<&str>,
+    ) -> VaultResult<Option<String>> {
+        let revision = metadata.encryption_revision();
+        let archives = self.list_archives(metadata.vault_id())?;
+
+        let Some(archive_id) = archive_id else {
+            return Ok(archives
+                .iter()
+                .rev()
+                .find(|entry| entry.encryption_revision == revision)
+                .map(|entry| entry.archive_id.clone()));
+        };
+
+        let entry = archives
+            .iter()
+            .find(|entry| entry.archive_id == archive_id)
+            .ok_or_else(|| VaultError::NotFound(format!("archive {}", archive_id)))?;
+        if entry.encryption_revision != revision {
+            return Err(VaultError::InvalidOperation(format!(
+                "Archive '{}' is revision {}, but only the latest archive's file list \
+                 (revision {}) is kept locally",
+                entry.archive_name, entry.encryption_revision, revision
+            )));
+        }
+        Ok(Some(entry.archive_id.clone()))
+    }
+
+    /// Refuse if the archive at `archive_name` is marked immutable
+    ///
+    /// Called before anything writes over or removes an archive file.
+    pub fn ensure_replaceable(&self, archive_name: &str) -> VaultResult<()> {
+        Self::check_replaceable(&load_index()?, archive_name)
+    }
+
+    /// Ranked matches for `query` among a vault's archives
+    pub fn search_archives(
+        &self,
+        vault_id: &str,
+        query: &str,
+    ) -> VaultResult<Vec<ArchiveSearchMatch>> {
+        let index = load_index()?;
+        let matches = search_archive_entries(index.entries(vault_id), query);
+        debug!(
+            vault_id,
+            query_len = query.chars().count(),
+            match_count = matches.len(),
+            "Searched archive index"
+        );
+        Ok(matches)
+    }
+
+    /// Replace an archive's comment in the index and, for the latest
+    /// archive, in the external manifest
+    ///
+    /// `sanitized_name` locates the vault's external manifest.
+    pub fn update_archive_comment(
+        &self,
+        vault_id: &str,
+        sanitized_name: &str,
+        archive_id: &str,
+        comment: Option<&str>,
+    ) -> VaultResult<ArchiveIndexEntry> {
+        let comment = validate_archive_comment(comment)?;
+
+        let mut index = load_index()?;
+        let entry = Self::apply_comment(&mut index, vault_id, archive_id, comment, Utc::now())?;
+        save_index("update_archive_comment", &index)?;
+        self.file_search.update_comment(&entry);
+
+        // The external manifest describes only the latest encryption
+        let manifest = self
+            .metadata_service
+            .load_saved(sanitized_name)
+            .map_err(|e| VaultError::StorageError(e.to_string()))?;
+        if let Some(mut manifest) = manifest
+            && manifest.encryption_revision() == entry.encryption_revision
+        {
+            manifest.comment = entry.comment.clone();
+            manifest.comment_updated_at = entry.comment_updated_at;
+            self.metadata_service
+                .save_manifest(&manifest)
+                .map_err(|e| VaultError::StorageError(e.to_string()))?;
+        }
+
+        Ok(entry)
+    }
+
+    /// Append an entry for `manifest` to the index
+    pub fn record_in(
+        index: &mut ArchiveIndex,
+        manifest: &VaultMetadata,
+        archive_name: &str,
+    ) -> ArchiveIndexEntry {
+        let entry = ArchiveIndexEntry {
+            archive_id: uuid::Uuid::new_v4().to_string(),
+            vault_id: manifest.vault_id().to_string(),
+            archive_name: archive_name.to_string(),
+            encryption_revision: manifest.encryption_revision(),
+            created_at: manifest.last_encrypted_at().unwrap_or_else(Utc::now),
+            file_count: manifest.file_count(),
+            comment: manifest.comment.clone(),
+            comment_updated_at: None,
+            immutable: false,
+            parity: None,
+            migrated_from: None,
+            prune_marked: false,
+            remapped_from: None,
+            archive_sha256: None,
+            recipients: Vec::new(),
+        };
+
+        debug!(
+            vault_id = %entry.vault_id,
+            archive_id = %entry.archive_id,
+            revision = entry.encryption_revision,
+            comment_len = comment_len(&entry.comment),
+            "Recorded archive"
+        );
+
+        index.record(entry.clone());
+        entry
+    }
+
+    /// Listings for a vault, with `os_protected` probed for current archives
+    pub fn listings(
+        index: &ArchiveIndex,
+        vault_id: &str,
+        os_protected: impl Fn(&str) -> bool,
+    ) -> Vec<ArchiveListing> {
+        index
+            .entries(vault_id)
+            .iter()
+            .map(|entry| {
+                let current = is_current(index, entry);
+                ArchiveListing {
+                    entry: entry.clone(),
+                    current,
+                    os_protected: current && os_protected(&entry.archive_name),
+                    retention: None,
+                }
+            })
+            .collect()
+    }
+
+    /// Set or clear an indexed archive's immutable flag
+    ///
+    /// Only the current archive can be marked, since older encryptions no
+    /// longer exist on disk. Returns the entry and whether it's current.
+    pub fn apply_immutable(
+        index: &mut ArchiveIndex,
+        vault_id: &str,
+        archive_id: &str,
+        immutable: bool,
+        confirmation: Option<&str>,
+    ) -> VaultResult<(ArchiveIndexEntry, bool)> {
+        let entry = index
+            .entries(vault_id)
+            .iter()
+            .find(|entry| entry.archive_id == archive_id)
+            .cloned()
+            .ok_or_else(|| {
+                VaultError::InvalidOperation(format!("Archive '{}' not found", archive_id))
+            })?;
+        let current = is_current(index, &entry);
+
+        if immutable && !current {
+            return Err(VaultError::InvalidOperation(format!(
+                "Archive '{}' has been replaced by a later encryption",
+                archive_id
+            )));
+        }
+        if !immutable && entry.immutable && confirmation != Some(CLEAR_IMMUTABLE_CONFIRMATION) {
+            return Err(VaultError::InvalidOperation(format!(
+                "Type '{}' to confirm clearing the immutable flag",
+                CLEAR_IMMUTABLE_CONFIRMATION
+            )));
+        }
+
+        if let Some(stored) = index.find_mut(vault_id, archive_id) {
+            stored.immutable = immutable;
+        }
+
+        info!(
+            vault_id,
+            archive_id, immutable, "Updated archive immutability"
+        );
+        Ok((ArchiveIndexEntry { immutable, ..entry }, current))
+    }
+
+    /// Record `remapped_from` on a remapped archive's entry
+    ///
+    /// The original is left as it is. Returns the remapped entry.
+    pub fn apply_remap_link(
+        index: &mut ArchiveIndex,
+        vault_id: &str,
+        archive_id: &str,
+        remapped_from: &str,
+    ) -> VaultResult<ArchiveIndexEntry> {
+        check_link_ends(index, vault_id, archive_id, remapped_from)?;
+
+        let linked = index.find_mut(vault_id, archive_id).map(|remapped| {
+            remapped.remapped_from = Some(remapped_from.to_string());
+            remapped.clone()
+        });
+
+        info!(
+            vault_id,
+            archive_id, remapped_from, "Linked remapped archive"
+        );
+        linked.ok_or_else(|| {
+            VaultError::InvalidOperation(format!("Archive '{}' not found", archive_id))
+        })
+    }
+
+    /// Record `migrated_from` on a migrated archive's entry
+    ///
+    /// With `prune_original`, the original is marked eligible for pruning.
+    /// Returns the migrated entry.
+    pub fn apply_migration_link(
+        index: &mut ArchiveIndex,
+        vault_id: &str,
+        archive_id: &str,
+        migrated_from: &str,
+        prune_original: bool,
+    ) -> VaultResult<ArchiveIndexEntry> {
+        check_link_ends(index, vault_id, archive_id, migrated_from)?;
+
+        if let Some(original) = index.find_mut(vault_id, migrated_from) {
+            original.prune_marked |= prune_original;
+        }
+        let mut linked = None;
+        if let Some(migrated) = index.find_mut(vault_id, archive_id) {
+            migrated.migrated_from = Some(migrated_from.to_string());
+            linked = Some(migrated.clone());
+        }
+
+        info!(
+            vault_id,
+            archive_id, migrated_from, prune_original, "Linked migrated archive"
+        );
+        linked.ok_or_else(|| {
+            VaultError::InvalidOperation(format!("Archive '{}' not found", archive_id))
+        })
+    }
+
+    /// Remove archives from a vault's index entries
+    ///
+    /// Returns each removed entry and whether its archive file is now left
+    /// without an entry. Nothing is removed if any archive is refused.
+    pub fn apply_prune(
+        index: &mut ArchiveIndex,
+        vault_id: &str,
+        archive_ids: &[String],
+    ) -> VaultResult<Vec<(ArchiveIndexEntry, bool)>> {
+        let entries = index.entries(vault_id);
+        let latest = latest_archive(entries).map(|entry| entry.archive_id.as_str());
+        for archive_id in archive_ids {
+            let entry = entries
+                .iter()
+                .find(|entry| &entry.archive_id == archive_id)
+                .ok_or_else(|| {
+                    VaultError::InvalidOperation(format!("Archive '{}' not found", archive_id))
+                })?;
+            if latest == Some(archive_id.as_str()) {
+                return Err(VaultError::InvalidOperation(format!(
+                    "Archive '{}' is the vault's latest and can't be pruned",
+                    archive_id
+                )));
+            }
+            if entry.immutable {
+                return Err(VaultError::ArchiveImmutable {
+                    archive_id: entry.archive_id.clone(),
+                    archive_name: entry.archive_name.clone(),
+                });
+            }
+        }
+
+        let orphaned: HashSet<String> = Self::orphaned_by_prune(index, vault_id, archive_ids)
+            .into_iter()
+            .map(|entry| entry.archive_id.clone())
+            .collect();
+        let removed = archive_ids
+            .iter()
+            .filter_map(|archive_id| index.remove(vault_id, archive_id))
+            .map(|entry| {
+                let orphaned = orphaned.contains(&entry.archive_id);
+                (entry, orphaned)
+            })
+            .collect();
+        Ok(removed)
+    }
+
+    /// Entries among `archive_ids` holding an archive file that no other
+    /// entry refers to
+    fn orphaned_by_prune<'a>(
+        index: &'a ArchiveIndex,
+        vault_id: &str,
+        archive_ids: &[String],
+    ) -> Vec<&'a ArchiveIndexEntry> {
+        index
+            .entries(vault_id)
+            .iter()
+            .filter(|entry| archive_ids.contains(&entry.archive_id) && is_current(index, entry))
+            .filter(|entry| {
+                index
+                    .entries_named(&entry.archive_name)
+                    .all(|other| archive_ids.contains(&other.archive_id))
+            })
+            .collect()
+    }
+
+    /// Delete an archive file and its parity sidecar, adding what was freed
+    ///
+    /// Returns whether the archive file itself was deleted.
+    fn delete_archive_files(
+        &self,
+        vaults_dir: &Path,
+        entry: &ArchiveIndexEntry,
+        freed: &mut ByteSize,
+    ) -> bool {
+        let mut archive_deleted = false;
+        for (position, path) in archive_files(vaults_dir, entry).into_iter().enumerate() {
+            if !path.exists() {
+                continue;
+            }
+            let size = file_size(&path);
+            match self.deleter.delete_file(&path) {
+                Ok(_) => {
+                    *freed += ByteSize(size);
+                    archive_deleted |= position == 0;
+                }
+                Err(e) => warn!(
+                    archive_id = %entry.archive_id,
+                    path = %path.display(),
+                    error = %e,
+                    "Failed to delete pruned archive file"
+                ),
+            }
+        }
+        archive_deleted
+    }
+
+    /// Refuse if the current archive at `archive_name` is immutable
+    pub fn check_replaceable(index: &ArchiveIndex, archive_name: &str) -> VaultResult<()> {
+        match index.current_entry(archive_name) {
+            Some(entry) if entry.immutable => {
+                warn!(archive_id = %entry.archive_id, "Refused to replace immutable archive");
+                Err(VaultError::ArchiveImmutable {
+                    archive_id: entry.archive_id.clone(),
+                    archive_name: entry.archive_name.clone(),
+                })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Set an indexed archive's comment and edit timestamp
+    pub fn apply_comment(
+        index: &mut ArchiveIndex,
+        vault_id: &str,
+        archive_id: &str,
+        comment: Option<String>,
+        now: DateTime<Utc>,
+    ) -> VaultResult<ArchiveIndexEntry> {
+        let entry = index.find_mut(vault_id, archive_id).ok_or_else(|| {
+            VaultError::InvalidOperation(format!("Archive '{}' not found", archive_id))
+        })?;
+
+        entry.comment = comment;
+        entry.comment_updated_at = Some(now);
+
+        info!(
+            vault_id,
+            archive_id,
+            comment_len = comment_len(&entry.comment),
+            "Updated archive comment"
+        );
+
+        Ok(entry.clone())
+    }
+}
+
+impl Default for ArchiveService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn comment_len(comment: &Option<String>) -> usize {
+    comment.as_deref().map_or(0, |c| c.chars().count())
+}
+
+fn is_current(index: &ArchiveIndex, entry: &ArchiveIndexEntry) -> bool {
+    index
+        .current_entry(&entry.archive_name)
+        .is_some_and(|current| current.archive_id == entry.archive_id)
+}
+
+/// An archive's file followed by its parity sidecar, if it has one
+/// Both archives of a migration or remap link are distinct entries of the vault
+fn check_link_ends(
+    index: &ArchiveIndex,
+    vault_id: &str,
+    archive_id: &str,
+    original_id: &str,
+) -> VaultResult<()> {
+    if archive_id == original_id {
+        return Err(VaultError::InvalidOperation(
+            "An archive can't be linked to itself".to_string(),
+        ));
+    }
+    for id in [archive_id, original_id] {
+        if !index
+            .entries(vault_id)
+            .iter()
+            .any(|entry| entry.archive_id == id)
+        {
+            return Err(VaultError::InvalidOperation(format!(
+                "Archive '{}' not found",
+                id
+            )));
+        }
+    }
+    Ok(())
+}
+
+fn archive_files(vaults_dir: &Path, entry: &ArchiveIndexEntry) -> Vec<PathBuf> {
+    std::iter::once(&entry.archive_name)
+        .chain(entry.parity.as_ref().map(|parity| &parity.file_name))
+        .map(|name| vaults_dir.join(name))
+        .collect()
+}
+
+fn file_size(path: &Path) -> u64 {
+    std::fs::metadata(path).map_or(0, |metadata| metadata.len())
+}
+
+fn vaults_dir() -> VaultResult<PathBuf> {
+    get_vaults_directory().map_err(|e| VaultError::StorageError(e.to_string()))
+}
+
+fn load_index() -> VaultResult<ArchiveIndex> {
+    ArchiveIndex::load().map_err(|e| VaultError::StorageError(e.to_string()))
+}
+
+/// Save the index, taking a metadata restore point first
+fn save_index(operation: &str, index: &ArchiveIndex) -> VaultResult<()> {
+    snapshot_before(operation);
+    index.save().map_err(storage_error)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::shared::infrastructure::DeviceInfo;
+    use tracing_test::traced_test;
+
+    const COMMENT: &str = "Grandma's letters and the deed scans";
+
+    fn manifest(comment: Option<&str>) -> VaultMetadata {
+        let device_info = DeviceInfo {
+            machine_id: "machine-1".to_string(),
+            machine_label: "laptop".to_string(),
+            created_at: Utc::now(),
+            app_version: "2.0.0".to_string(),
+        };
+        let mut manifest = VaultMetadata::new(
+            "vault-001".to_string(),
+            "Family".to_string(),
+            None,
+            "Family".to_string(),
+            &device_info,
+            None,
+            vec![],
+            vec![],
+            0,
+            0,
+        );
+        manifest.comment = comment.map(str::to_string);
+        manifest
+    }
+
+    #[test]
+    #[traced_test]
+    fn test_comment_text_never_logged() {
+        let mut index = ArchiveIndex::default();
+        let entry = ArchiveService::record_in(&mut index, &manifest(Some(COMMENT)), "Family.age");
+        assert_eq!(entry.comment.as_deref(), Some(COMMENT));
+
+        let edited = "Updated: deed scans only";
+        let updated = ArchiveService::apply_comment(
+            &mut index,
+            "vault-001",
+            &entry.archive_id,
+            Some(edited.to_string()),
+            Utc::now(),
+        )
+        .unwrap();
+        assert_eq!(updated.comment.as_deref(), Some(edited));
+        assert!(updated.comment_updated_at.is_some());
+
+        assert!(logs_contain("Recorded archive"));
+        assert!(logs_contain("Updated archive comment"));
+        assert!(!logs_contain("Grandma"));
+        assert!(!logs_contain("deed scans"));
+    }
+
+    #[test]
+    fn test_apply_comment_unknown_archive() {
+        let mut index = ArchiveIndex::default();
+        ArchiveService::record_in(&mut index, &manifest(None), "Family.age");
+
+        let result =
+            ArchiveService::apply_comment(&mut index, "vault-001", "missing", None, Utc::now());
+        assert!(matches!(result, Err(VaultError::InvalidOperation(_))));
+    }
+
+    #[test]
+    fn test_immutable_archive_refuses_replacement_until_cleared() {
+        let mut index = ArchiveIndex::default();
+        let entry = ArchiveService::record_in(&mut index, &manifest(None), "Family.age");
+        let id = entry.archive_id.as_str();
+
+        let (marked, current) =
+            ArchiveService::apply_immutable(&mut index, "vault-001", id, true, None).unwrap();
+        assert!(marked.immutable && current);
+        assert!(matches!(
+            ArchiveService::check_replaceable(&index, "Family.age"),
+            Err(VaultError::ArchiveImmutable { .. })
+        ));
+        assert!(ArchiveService::check_replaceable(&index, "Other.age").is_ok());
+
+        for confirmation in [None, Some("unlock"), Some("DELETE")] {
+            let result =
+                ArchiveService::apply_immutable(&mut index, "vault-001", id, false, confirmation);
+            assert!(matches!(result, Err(VaultError::InvalidOperation(_))));
+        }
+        assert!(index.entries("vault-001")[0].immutable);
+
+        let (cleared, _) = ArchiveService::apply_immutable(
+            &mut index,
+            "vault-001",
+            id,
+            false,
+            Some(CLEAR_IMMUTABLE_CONFIRMATION),
+        )
+        .unwrap();
+        assert!(!cleared.immutable);
+        assert!(ArchiveService::check_replaceable(&index, "Family.age").is_ok());
+    }
+
+    #[test]
+    fn test_only_current_archive_can_be_marked_immutable() {
+        let mut index = ArchiveIndex::default();
+        let older = ArchiveService::record_in(&mut index, &manifest(None), "Family.age");
+        index.vaults.get_mut("vault-001").unwrap()[0].created_at -= chrono::Duration::days(1);
+        let latest = ArchiveService::record_in(&mut index, &manifest(None), "Family.age");
+
+        let result =
+            ArchiveService::apply_immutable(&mut index, "vault-001", &older.archive_id, true, None);
+        assert!(matches!(result, Err(VaultError::InvalidOperation(_))));
+
+        ArchiveService::apply_immutable(&mut index, "vault-001", &latest.archive_id, true, None)
+            .unwrap();
+        let listings = ArchiveService::listings(&index, "vault-001", |_| true);
+        assert_eq!(
+            listings
+                .iter()
+                .map(|l| (l.current, l.os_protected, l.entry.immutable))
+                .collect::<Vec<_>>(),
+            vec![(false, false, false), (true, true, true)]
+        );
+    }
+
+    #[test]
+    fn test_prune_keeps_latest_and_immutable_archives() {
+        let mut index = ArchiveIndex::default();
+        let renamed = ArchiveService::record_in(&mut index, &manifest(None), "Old-Name.age");
+        let older = ArchiveService::record_in(&mut index, &manifest(None), "Family.age");
+        let latest = ArchiveService::record_in(&mut index, &manifest(None), "Family.age");
+        for (position, days) in [(0, 3), (1, 2)] {
+            index.vaults.get_mut("vault-001").unwrap()[position].created_at -=
+                chrono::Duration::days(days);
+        }
+
+        let refused = [latest.archive_id.clone(), older.archive_id.clone()];
+        assert!(matches!(
+            ArchiveService::apply_prune(&mut index, "vault-001", &refused),
+            Err(VaultError::InvalidOperation(_))
+        ));
+        index.vaults.get_mut("vault-001").unwrap()[0].immutable = true;
+        assert!(matches!(
+            ArchiveService::apply_prune(&mut index, "vault-001", &[renamed.archive_id.clone()]),
+            Err(VaultError::ArchiveImmutable { .. })
+        ));
+        assert_eq!(index.entries("vault-001").len(), 3);
+
+        index.vaults.get_mut("vault-001").unwrap()[0].immutable = false;
+        let removed = ArchiveService::apply_prune(
+            &mut index,
+            "vault-001",
+            &[renamed.archive_id.clone(), older.archive_id.clone()],
+        )
+        .unwrap();
+        // Only the renamed archive's file is left without an entry
+        assert_eq!(
+            removed
+                .iter()
+                .map(|(entry, orphaned)| (entry.archive_name.as_str(), *orphaned))
+                .collect::<Vec<_>>(),
+            vec![("Old-Name.age", true), ("Family.age", false)]
+        );
+        assert_eq!(index.entries("vault-001"), [latest]);
+    }
+
+    #[test]
+    fn test_migration_links_back_to_original() {
+        let mut index = ArchiveIndex::default();
+        let original = ArchiveService::record_in(&mut index, &manifest(None), "Family.age");
+        let migrated = ArchiveService::record_in(&mut index, &manifest(None), "Family.age");
+
+        assert!(matches!(
+            ArchiveService::apply_migration_link(
+                &mut index,
+                "vault-001",
+                &migrated.archive_id,
+                "missing",
+                true
+            ),
+            Err(VaultError::InvalidOperation(_))
+        ));
+
+        let linked = ArchiveService::apply_migration_link(
+            &mut index,
+            "vault-001",
+            &migrated.archive_id,
+            &original.archive_id,
+            true,
+        )
+        .unwrap();
+        assert_eq!(
+            linked.migrated_from.as_deref(),
+            Some(original.archive_id.as_str())
+        );
+        let entries = index.entries("vault-001");
+        assert!(entries[0].prune_marked);
+        assert!(!entries[1].prune_marked);
+        assert_eq!(entries[1], linked);
+    }
+
+    #[test]
+    fn test_remap_links_back_and_keeps_the_original() {
+        let mut index = ArchiveIndex::default();
+        let original = ArchiveService::record_in(&mut index, &manifest(None), "Family.age");
+        let remapped =
+            ArchiveService::record_in(&mut index, &manifest(None), "Family-remapped.age");
+
+        assert!(
+            ArchiveService::apply_remap_link(
+                &mut index,
+                "vault-001",
+                &remapped.archive_id,
+                &remapped.archive_id
+            )
+            .is_err()
+        );
+
+        let linked = ArchiveService::apply_remap_link(
+            &mut index,
+            "vault-001",
+            &remapped.archive_id,
+            &original.archive_id,
+        )
+        .unwrap();
+        assert_eq!(
+            linked.remapped_from.as_deref(),
+            Some(original.archive_id.as_str())
+        );
+        assert_eq!(linked.migrated_from, None);
+        assert_eq!(index.entries("vault-001")[0], original);
+    }
+}
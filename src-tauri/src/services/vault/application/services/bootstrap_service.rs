@@ -1,15 +1,31 @@
 //! Bootstrap Service
 //!
-//! Handles application startup initialization: device identity, manifest scanning,
-//! and registry synchronization from vault manifests.
+//! Handles application startup initialization: device identity, journal recovery,
+//! migration of legacy profiles, manifest scanning, quarantine of incomplete
+//! archives, review of operations a forced quit interrupted, storage quota
+//! cleanup, and registry synchronization from vault manifests.
 
 use crate::error::StorageError;
 use crate::prelude::*;
-use crate::services::key_management::shared::KeyRegistry;
+use crate::services::key_management::shared::application::services::LegacyMigrationService;
 use crate::services::key_management::shared::application::services::registry_service::{
     KeyRegistryService, MergeStrategy,
 };
-use crate::services::shared::infrastructure::{DeviceInfo, get_vaults_manifest_dir};
+use crate::services::key_management::shared::domain::models::MigrationReport;
+use crate::services::key_management::shared::{KeyRegistry, LabelRename};
+use crate::services::shared::infrastructure::io::recover_interrupted_swaps;
+use crate::services::shared::infrastructure::{
+    DeviceInfo, MutationJournal, RecoveryReport, get_config_dir, get_keys_dir,
+    get_vaults_directory, get_vaults_manifest_dir,
+};
+use crate::services::vault;
+use crate::services::vault::application::services::{
+    QuarantineService, ShutdownService, StorageQuotaService,
+};
+use crate::services::vault::domain::models::{
+    IncompleteArchiveReport, InterruptedOperation, StorageCleanupReport,
+};
+use crate::services::vault::domain::{NameKind, NameValidator};
 use crate::services::vault::infrastructure::persistence::metadata::VaultMetadata;
 
 /// Bootstrap service for app initialization
@@ -25,9 +41,15 @@ pub fn new() -> Self {
     ///
     /// Performs:
     /// 1. Load/generate device.json
+    ///    (then replay or roll back mutations interrupted by a crash, and
+    ///    migrate profiles written by older builds)
     /// 2. Scan vaults/ directory for manifests
+    ///    (then quarantine archives left incomplete by an interrupted run,
+    ///    note it against operations a forced quit interrupted, and purge
+    ///    app data over its storage quotas)
     /// 3. Load key registry
     /// 4. Additive merge: manifests → registry
+    ///    (then rename duplicated key labels, updating manifests to match)
     /// 5. Detect and merge YubiKeys (TODO - future)
     /// 6. Save updated registry
     pub async fn bootstrap(&self) -> Result<BootstrapResult, StorageError> {
@@ -42,11 +64,27 @@ pub async fn bootstrap(&self) -> Result<BootstrapResult, StorageError> {
             "Device identity loaded"
         );
 
+        // Finish or undo single-file swaps cut short on locations without
+        // atomic rename, then resolve interrupted registry/manifest mutations
+        self.recover_interrupted_swaps();
+        let journal_recovery = MutationJournal::open()?.recover()?;
+
+        // Step 1b: Bring keys from older builds' locations into the current
+        // ones before anything reads the registry
+        let legacy_migration = self.migrate_legacy_profiles();
+
         // Step 2: Scan for vault manifests
-        let manifests = self.scan_vault_manifests().await?;
+        let mut manifests = self.scan_vault_manifests().await?;
 
         info!(manifest_count = manifests.len(), "Scanned vault manifests");
 
+        // Step 2b: Quarantine archives left incomplete by an interrupted run
+        let incomplete_archives = self.quarantine_incomplete_archives(&manifests);
+        let interrupted_operations = self.review_interrupted_operations(&incomplete_archives);
+
+        // Step 2c: Keep staging, snapshots, logs etc. within their quotas
+        let storage_cleanup = self.enforce_storage_quotas();
+
         // Step 3: Load or create key registry
         let mut registry = KeyRegistry::load().map_err(|e| StorageError::InvalidFormat {
             path: std::path::PathBuf::from("registry"),
@@ -58,15 +96,38 @@ pub async fn bootstrap(&self) -> Result<BootstrapResult, StorageError> {
         // Step 4: Additive merge from manifests to registry
         let merge_stats = self.merge_manifests_to_registry(&mut registry, &manifests)?;
 
+        // Step 4b: Make key labels unique (duplicates from older versions or merges)
+        let (label_renames, relabelled) = self.reconcile_key_labels(&mut registry, &mut manifests);
+
+        // Names from before validation existed keep working; just flag them
+        self.warn_nonconforming_names(&registry, &manifests);
+
         // Step 5: TODO - Detect and merge YubiKeys
         // let yubikey_stats = self.detect_and_merge_yubikeys(&mut registry).await?;
 
-        // Step 6: Save updated registry (atomic write)
-        registry
-            .save()
-            .map_err(|e| StorageError::SerializationFailed {
-                message: format!("Failed to save registry: {}", e),
-            })?;
+        // Step 6: Save updated registry (atomic write), together with any
+        // manifests whose recipient labels changed
+        if relabelled.is_empty() {
+            registry
+                .save()
+                .map_err(|e| StorageError::SerializationFailed {
+                    message: format!("Failed to save registry: {}", e),
+                })?;
+        } else {
+            let mut writes = vec![registry.to_pending_write().map_err(|e| {
+                StorageError::SerializationFailed {
+                    message: format!("Failed to serialize registry: {}", e),
+                }
+            })?];
+            for index in &relabelled {
+                writes.push(vault::vault_pending_write(&manifests[*index]).map_err(|e| {
+                    StorageError::SerializationFailed {
+                        message: format!("Failed to serialize manifest: {}", e),
+                    }
+                })?);
+            }
+            MutationJournal::open()?.apply("normalize_key_labels", writes)?;
+        }
 
         info!(
             initial_keys = initial_key_count,
@@ -82,9 +143,110 @@ pub async fn bootstrap(&self) -> Result<BootstrapResult, StorageError> {
             keys_before: initial_key_count,
             keys_after: registry.keys.len(),
             keys_added: merge_stats.keys_added,
+            journal_recovery,
+            legacy_migration,
+            label_renames,
+            incomplete_archives,
+            interrupted_operations,
+            storage_cleanup,
+        })
+    }
+
+    /// Resolve safe swaps interrupted in the directories the app writes to
+    fn recover_interrupted_swaps(&self) {
+        let dirs = [
+            get_config_dir(),
+            get_keys_dir(),
+            get_vaults_manifest_dir(),
+            get_vaults_directory(),
+        ];
+        for dir in dirs.into_iter().flatten() {
+            match recover_interrupted_swaps(&dir) {
+                Ok(recovered) => {
+                    for (path, action) in recovered {
+                        info!(path = %path.display(), ?action, "Recovered interrupted file swap");
+                    }
+                }
+                Err(e) => {
+                    warn!(dir = %dir.display(), error = %e, "Failed to recover file swaps")
+                }
+            }
+        }
+    }
+
+    /// Migrate keys from legacy profile locations
+    ///
+    /// Failures are logged and don't stop startup; conflicts are left for
+    /// the user to resolve and kept in the migration report.
+    fn migrate_legacy_profiles(&self) -> Option<MigrationReport> {
+        LegacyMigrationService::new().run().unwrap_or_else(|e| {
+            warn!(error = %e, "Failed to migrate legacy profile");
+            None
         })
     }
 
+    /// Move each vault's incomplete archives into quarantine
+    ///
+    /// Failures are logged and don't stop startup.
+    fn quarantine_incomplete_archives(
+        &self,
+        manifests: &[VaultMetadata],
+    ) -> Vec<IncompleteArchiveReport> {
+        let quarantine = QuarantineService::new();
+        manifests
+            .iter()
+            .filter_map(|manifest| match quarantine.scan_vault(manifest) {
+                Ok(report) => (!report.artifacts.is_empty()).then_some(report),
+                Err(e) => {
+                    warn!(
+                        vault = %manifest.label(),
+                        error = %e,
+                        "Failed to check for incomplete archives"
+                    );
+                    None
+                }
+            })
+            .collect()
+    }
+
+    /// Pick up operations the last run's forced quit interrupted, for the
+    /// notifications digest
+    ///
+    /// Failures are logged and don't stop startup.
+    fn review_interrupted_operations(
+        &self,
+        incomplete_archives: &[IncompleteArchiveReport],
+    ) -> Vec<(String, InterruptedOperation)> {
+        let reviewed = ShutdownService::new()
+            .review_interrupted(incomplete_archives)
+            .unwrap_or_else(|e| {
+                warn!(error = %e, "Failed to review interrupted operations");
+                Vec::new()
+            });
+        for (vault_id, record) in &reviewed {
+            warn!(
+                vault_id,
+                operation = %record.operation,
+                interrupted_at = %record.interrupted_at,
+                quarantined_files = record.quarantined_files.unwrap_or_default(),
+                "Operation was interrupted by a forced quit"
+            );
+        }
+        reviewed
+    }
+
+    /// Purge app data over its storage quotas
+    ///
+    /// Failures are logged and don't stop startup.
+    fn enforce_storage_quotas(&self) -> StorageCleanupReport {
+        StorageQuotaService::new()
+            .enforce_quotas()
+            .unwrap_or_else(|e| {
+                warn!(error = %e, "Failed to enforce storage quotas");
+                StorageCleanupReport::default()
+            })
+    }
+
     /// Scan vaults manifest directory for all .manifest files
     async fn scan_vault_manifests(&self) -> Result<Vec<VaultMetadata>, StorageError> {
         let vaults_manifest_dir = get_vaults_manifest_dir()?;
@@ -140,6 +302,62 @@ async fn scan_vault_manifests(&self) -> Result<Vec<VaultMetadata>, StorageError>
         Ok(manifests)
     }
 
+    /// Log vault names and key labels that new writes would refuse
+    fn warn_nonconforming_names(&self, registry: &KeyRegistry, manifests: &[VaultMetadata]) {
+        for manifest in manifests {
+            if let Some(reason) =
+                NameValidator::nonconformity(NameKind::VaultName, manifest.label())
+            {
+                warn!(
+                    vault_id = %manifest.vault_id(),
+                    reason = %reason,
+                    "Vault name does not meet current naming rules"
+                );
+            }
+        }
+
+        for (key_id, entry) in &registry.keys {
+            if let Some(reason) = NameValidator::nonconformity(NameKind::KeyLabel, entry.label()) {
+                warn!(
+                    key_id = %key_id,
+                    reason = %reason,
+                    "Key label does not meet current naming rules"
+                );
+            }
+        }
+    }
+
+    /// Rename duplicated key labels and carry the renames into the manifests
+    ///
+    /// Returns the renames and the indices of manifests that changed.
+    fn reconcile_key_labels(
+        &self,
+        registry: &mut KeyRegistry,
+        manifests: &mut [VaultMetadata],
+    ) -> (Vec<LabelRename>, Vec<usize>) {
+        let renames = registry.normalize_labels();
+        if renames.is_empty() {
+            return (renames, Vec::new());
+        }
+
+        let relabelled: Vec<usize> = manifests
+            .iter_mut()
+            .enumerate()
+            .filter_map(|(index, manifest)| {
+                KeyRegistryService::relabel_recipients(registry, &renames, manifest)
+                    .then_some(index)
+            })
+            .collect();
+
+        info!(
+            renamed = renames.len(),
+            manifests_updated = relabelled.len(),
+            "Renamed duplicate key labels during bootstrap"
+        );
+
+        (renames, relabelled)
+    }
+
     /// Merge recipients from manifests into registry (additive only)
     ///
     /// Never removes keys from registry - preserves unattached keys.
@@ -192,6 +410,18 @@ pub struct BootstrapResult {
     pub keys_before: usize,
     pub keys_after: usize,
     pub keys_added: usize,
+    /// Mutations replayed or rolled back from the write-ahead journal
+    pub journal_recovery: RecoveryReport,
+    /// Set when a profile from an older build was found
+    pub legacy_migration: Option<MigrationReport>,
+    /// Duplicate key labels renamed during reconciliation
+    pub label_renames: Vec<LabelRename>,
+    /// Vaults where incomplete archives were found
+    pub incomplete_archives: Vec<IncompleteArchiveReport>,
+    /// Operations the last run's forced quit interrupted, by vault ID
+    pub interrupted_operations: Vec<(String, InterruptedOperation)>,
+    /// App data purged because its category was over quota
+    pub storage_cleanup: StorageCleanupReport,
 }
 
 /// Statistics from manifest merge operation
@@ -210,5 +440,111 @@ fn test_bootstrap_service_creation() {
         let _service = BootstrapService::new();
     }
 
+    #[test]
+    fn test_reconcile_fixes_duplicated_registry() {
+        use crate::services::key_management::shared::KeyEntry;
+        use crate::services::key_management::shared::domain::models::key_lifecycle::KeyLifecycleStatus;
+        use crate::services::vault::infrastructure::persistence::metadata::RecipientInfo;
+
+        let passphrase_entry = |label: &str, public_key: &str, created_at| KeyEntry::Passphrase {
+            label: label.to_string(),
+            created_at,
+            last_used: None,
+            public_key: public_key.to_string(),
+            key_filename: format!("{}.agekey.enc", public_key),
+            lifecycle_status: KeyLifecycleStatus::Active,
+            status_history: vec![],
+            vault_associations: vec!["vault-1".to_string()],
+            deactivated_at: None,
+            previous_lifecycle_status: None,
+            key_location: None,
+            passphrase_policy: 0,
+        };
+
+        let older = chrono::Utc::now() - chrono::Duration::days(30);
+        let newer = chrono::Utc::now();
+
+        let mut registry = KeyRegistry::new();
+        registry.keys.insert(
+            "Work-Key".to_string(),
+            passphrase_entry("Work Key", "age1old", older),
+        );
+        registry.keys.insert(
+            "Work-Key-2".to_string(),
+            passphrase_entry("Work Key", "age1new", newer),
+        );
+
+        let device_info = DeviceInfo {
+            machine_id: "test-machine".to_string(),
+            machine_label: "test-laptop".to_string(),
+            created_at: chrono::Utc::now(),
+            app_version: "2.0.0".to_string(),
+        };
+        let manifest = VaultMetadata::new(
+            "vault-1".to_string(),
+            "Vault One".to_string(),
+            None,
+            "Vault-One".to_string(),
+            &device_info,
+            None,
+            vec![
+                RecipientInfo::new_passphrase(
+                    "Work-Key".to_string(),
+                    "age1old".to_string(),
+                    "Work Key".to_string(),
+                    "Work-Key.agekey.enc".to_string(),
+                ),
+                RecipientInfo::new_passphrase(
+                    "Work-Key-2".to_string(),
+                    "age1new".to_string(),
+                    "Work Key".to_string(),
+                    "Work-Key-2.agekey.enc".to_string(),
+                ),
+            ],
+            vec![],
+            0,
+            0,
+        );
+        let untouched = VaultMetadata::new(
+            "vault-2".to_string(),
+            "Vault Two".to_string(),
+            None,
+            "Vault-Two".to_string(),
+            &device_info,
+            None,
+            vec![],
+            vec![],
+            0,
+            0,
+        );
+        let mut manifests = vec![manifest, untouched];
+
+        let service = BootstrapService::new();
+        let (renames, relabelled) = service.reconcile_key_labels(&mut registry, &mut manifests);
+
+        assert_eq!(renames.len(), 1);
+        assert_eq!(renames[0].key_id, "Work-Key-2");
+        assert_eq!(renames[0].new_label, "Work Key (2)");
+        assert_eq!(relabelled, vec![0]);
+
+        assert_eq!(registry.get_key("Work-Key").unwrap().label(), "Work Key");
+        assert_eq!(
+            registry.get_key("Work-Key-2").unwrap().label(),
+            "Work Key (2)"
+        );
+
+        let labels: Vec<&str> = manifests[0]
+            .recipients()
+            .iter()
+            .map(|r| r.label.as_str())
+            .collect();
+        assert_eq!(labels, vec!["Work Key", "Work Key (2)"]);
+
+        // A second bootstrap finds nothing to fix
+        let (renames, relabelled) = service.reconcile_key_labels(&mut registry, &mut manifests);
+        assert!(renames.is_empty());
+        assert!(relabelled.is_empty());
+    }
+
     // NOTE: Tests for generate_key_id and recipient_to_key_entry moved to KeyRegistryService tests
 }
@@ -0,0 +1,454 @@
+//! Clipboard Guard Service
+//!
+//! Puts a resolved value on the clipboard, records a redacted entry in the
+//! operation log, and clears the clipboard once the copy's time is up. Only
+//! the hash of the copied value is kept. The clear reads the clipboard back,
+//! compares hashes and drops what it read, so something the user copied
+//! since, in this app or another, is left alone.
+//!
+//! Each copy supersedes the one before; a clear scheduled for an earlier
+//! copy does nothing. The clipboard itself sits behind `ClipboardBackend`,
+//! so the guard is unit-testable without a desktop session.
+
+use crate::prelude::*;
+use crate::services::vault::application::services::OperationLogService;
+use crate::services::vault::domain::models::{
+    COPY_RECOVERY_CODE_CONFIRMATION, ClipboardGuardStatus, ClipboardKind, ClipboardSettings,
+    GuardedCopy, clipboard_hash,
+};
+use crate::services::vault::domain::{VaultError, VaultResult};
+use crate::services::vault::infrastructure::persistence::{
+    LoggedOperation, OperationLog, OperationLogEntry,
+};
+use crate::types::DurationMs;
+use chrono::{DateTime, Utc};
+use std::path::PathBuf;
+use std::sync::{Mutex, MutexGuard, OnceLock};
+use zeroize::Zeroize;
+
+/// The system clipboard, as text
+pub trait ClipboardBackend {
+    fn write_text(&self, text: &str) -> Result<(), String>;
+
+    /// Current text, `None` when the clipboard holds none
+    fn read_text(&self) -> Result<Option<String>, String>;
+
+    fn clear(&self) -> Result<(), String>;
+}
+
+/// A value to copy, already resolved from its reference
+pub struct ClipboardCopy<'a> {
+    pub kind: ClipboardKind,
+    /// ID the value was resolved from, kept in the operation log
+    pub value_ref: &'a str,
+    /// Vault the value belongs to, or the app-wide log scope
+    pub log_scope: &'a str,
+    pub value: &'a str,
+}
+
+#[derive(Debug, Clone)]
+struct Guarded {
+    kind: ClipboardKind,
+    hash: String,
+    copied_at: DateTime<Utc>,
+    clears_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Default)]
+struct GuardState {
+    /// Counts copies, so a clear knows whether its copy was superseded
+    generation: u64,
+    guarded: Option<Guarded>,
+}
+
+/// Clipboard copies of this app run
+#[derive(Debug, Default)]
+pub struct ClipboardGuard {
+    state: Mutex<GuardState>,
+    /// Operation log location; the config directory's log when `None`
+    audit_log: Option<PathBuf>,
+}
+
+impl ClipboardGuard {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Guard recording copies to `audit_log` instead of the config directory
+    pub fn with_audit_log(audit_log: PathBuf) -> Self {
+        Self {
+            audit_log: Some(audit_log),
+            ..Self::default()
+        }
+    }
+
+    /// Guard of the running app
+    pub fn global() -> &'static Self {
+        static GUARD: OnceLock<ClipboardGuard> = OnceLock::new();
+        GUARD.get_or_init(Self::new)
+    }
+
+    fn lock(&self) -> MutexGuard<'_, GuardState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Refuse unless copying is on and, for recovery codes, confirmed
+    ///
+    /// Checked before the value is resolved, so an unconfirmed recovery
+    /// code is never read.
+    pub fn check_allowed(
+        settings: &ClipboardSettings,
+        kind: ClipboardKind,
+        confirmation: Option<&str>,
+    ) -> VaultResult<()> {
+        if !settings.enabled {
+            return Err(VaultError::InvalidOperation(
+                "Copying to the clipboard is turned off; turn it on in settings first".to_string(),
+            ));
+        }
+        if kind.requires_confirmation() && confirmation != Some(COPY_RECOVERY_CODE_CONFIRMATION) {
+            return Err(VaultError::InvalidOperation(format!(
+                "Type '{}' to confirm copying a {}",
+                COPY_RECOVERY_CODE_CONFIRMATION,
+                kind.as_str()
+            )));
+        }
+        Ok(())
+    }
+
+    /// Record the copy, put the value on the clipboard and start its time
+    ///
+    /// Returns the copy's generation, to pass to `clear_if_unchanged` once
+    /// `clear_after` has passed. Nothing is copied if it can't be recorded.
+    pub fn copy(
+        &self,
+        backend: &dyn ClipboardBackend,
+        copy: ClipboardCopy<'_>,
+        clear_after: DurationMs,
+        now: DateTime<Utc>,
+    ) -> VaultResult<u64> {
+        self.record(&copy, clear_after)?;
+        backend.write_text(copy.value).map_err(|e| {
+            VaultError::OperationFailed(format!("Failed to write to the clipboard: {e}"))
+        })?;
+
+        let mut state = self.lock();
+        state.generation += 1;
+        state.guarded = Some(Guarded {
+            kind: copy.kind,
+            hash: clipboard_hash(copy.value),
+            copied_at: now,
+            clears_at: now + chrono::Duration::milliseconds(clear_after.millis() as i64),
+        });
+
+        info!(
+            kind = copy.kind.as_str(),
+            clear_after_ms = clear_after.millis(),
+            "Copied to the clipboard"
+        );
+        Ok(state.generation)
+    }
+
+    /// Clear the clipboard if it still holds copy `generation`
+    ///
+    /// Returns whether it was cleared. A superseded copy is left to the
+    /// clear of the copy that replaced it.
+    pub fn clear_if_unchanged(
+        &self,
+        backend: &dyn ClipboardBackend,
+        generation: u64,
+    ) -> VaultResult<bool> {
+        let mut state = self.lock();
+        if state.generation != generation {
+            return Ok(false);
+        }
+        let Some(guarded) = state.guarded.take() else {
+            return Ok(false);
+        };
+
+        let unchanged = match backend.read_text() {
+            Ok(Some(mut text)) => {
+                let matches = clipboard_hash(&text) == guarded.hash;
+                text.zeroize();
+                matches
+            }
+            Ok(None) => false,
+            Err(e) => {
+                return Err(VaultError::OperationFailed(format!(
+                    "Failed to read the clipboard: {e}"
+                )));
+            }
+        };
+        if !unchanged {
+            debug!(
+                kind = guarded.kind.as_str(),
+                "Clipboard changed since the copy"
+            );
+            return Ok(false);
+        }
+
+        backend.clear().map_err(|e| {
+            VaultError::OperationFailed(format!("Failed to clear the clipboard: {e}"))
+        })?;
+        info!(kind = guarded.kind.as_str(), "Cleared the clipboard");
+        Ok(true)
+    }
+
+    /// Clear the latest copy now instead of when its time is up
+    ///
+    /// Like `clear_if_unchanged`, leaves the clipboard alone if something
+    /// else was copied since.
+    pub fn clear_now(&self, backend: &dyn ClipboardBackend) -> VaultResult<bool> {
+        let generation = self.lock().generation;
+        self.clear_if_unchanged(backend, generation)
+    }
+
+    /// Whether a copy is waiting to be cleared
+    pub fn is_guarding(&self) -> bool {
+        self.lock().guarded.is_some()
+    }
+
+    /// The copy waiting to be cleared, with time left at `now`
+    pub fn status(&self, settings: ClipboardSettings, now: DateTime<Utc>) -> ClipboardGuardStatus {
+        let guarded = self.lock().guarded.as_ref().map(|guarded| GuardedCopy {
+            kind: guarded.kind,
+            copied_at: guarded.copied_at,
+            clears_at: guarded.clears_at,
+            remaining: (guarded.clears_at - now)
+                .to_std()
+                .unwrap_or_default()
+                .into(),
+        });
+        ClipboardGuardStatus { settings, guarded }
+    }
+
+    fn record(&self, copy: &ClipboardCopy<'_>, clear_after: DurationMs) -> VaultResult<()> {
+        let entry = OperationLogEntry::new(
+            LoggedOperation::ClipboardCopy,
+            copy.log_scope,
+            format!(
+                "Copied {} {} ({}) to the clipboard, to be cleared after {}",
+                copy.kind.as_str(),
+                copy.value_ref,
+                copy.kind.redact(copy.value),
+                clear_after
+            ),
+        );
+        match &self.audit_log {
+            Some(path) => OperationLog::record_in(path, entry)
+                .map(|_| ())
+                .map_err(|e| VaultError::StorageError(e.to_string())),
+            None => OperationLogService::new().record(entry),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::vault::infrastructure::persistence::APP_LOG_SCOPE;
+    use std::cell::RefCell;
+    use tempfile::TempDir;
+
+    const RECIPIENT: &str = "age1ql3z7hjy54pw3hyww5ayyfg7zqgvc7w3j2elw8zmrj2kg5sfn9aqmcac8p";
+    const SHARE: &str = "barqly-share-1-3-Zx8PqR2mT5vW9yB4";
+
+    #[derive(Default)]
+    struct FakeClipboard {
+        text: RefCell<Option<String>>,
+    }
+
+    impl ClipboardBackend for FakeClipboard {
+        fn write_text(&self, text: &str) -> Result<(), String> {
+            *self.text.borrow_mut() = Some(text.to_string());
+            Ok(())
+        }
+
+        fn read_text(&self) -> Result<Option<String>, String> {
+            Ok(self.text.borrow().clone())
+        }
+
+        fn clear(&self) -> Result<(), String> {
+            *self.text.borrow_mut() = None;
+            Ok(())
+        }
+    }
+
+    fn guard(temp_dir: &TempDir) -> ClipboardGuard {
+        ClipboardGuard::with_audit_log(temp_dir.path().join("operation_log.json"))
+    }
+
+    fn copy_of(kind: ClipboardKind, value: &str) -> ClipboardCopy<'_> {
+        ClipboardCopy {
+            kind,
+            value_ref: "key-1",
+            log_scope: APP_LOG_SCOPE,
+            value,
+        }
+    }
+
+    #[test]
+    fn test_clear_fires_only_while_the_copy_is_unchanged() {
+        let temp_dir = TempDir::new().unwrap();
+        let guard = guard(&temp_dir);
+        let clipboard = FakeClipboard::default();
+        let now = Utc::now();
+        let clear_after = DurationMs::from_secs(30);
+
+        let generation = guard
+            .copy(
+                &clipboard,
+                copy_of(ClipboardKind::Recipient, RECIPIENT),
+                clear_after,
+                now,
+            )
+            .unwrap();
+        let status = guard.status(ClipboardSettings::default(), now);
+        assert_eq!(status.guarded.unwrap().remaining, clear_after);
+
+        assert!(guard.clear_if_unchanged(&clipboard, generation).unwrap());
+        assert_eq!(clipboard.read_text().unwrap(), None);
+        assert!(
+            guard
+                .status(ClipboardSettings::default(), now)
+                .guarded
+                .is_none()
+        );
+
+        // Something copied since stays put
+        let generation = guard
+            .copy(
+                &clipboard,
+                copy_of(ClipboardKind::Recipient, RECIPIENT),
+                clear_after,
+                now,
+            )
+            .unwrap();
+        clipboard.write_text("copied in another app").unwrap();
+        assert!(!guard.clear_if_unchanged(&clipboard, generation).unwrap());
+        assert_eq!(
+            clipboard.read_text().unwrap().as_deref(),
+            Some("copied in another app")
+        );
+    }
+
+    #[test]
+    fn test_superseded_copy_is_not_cleared_early() {
+        let temp_dir = TempDir::new().unwrap();
+        let guard = guard(&temp_dir);
+        let clipboard = FakeClipboard::default();
+        let now = Utc::now();
+        let clear_after = DurationMs::from_secs(30);
+
+        let first = guard
+            .copy(
+                &clipboard,
+                copy_of(ClipboardKind::Recipient, RECIPIENT),
+                clear_after,
+                now,
+            )
+            .unwrap();
+        let second = guard
+            .copy(
+                &clipboard,
+                copy_of(ClipboardKind::Fingerprint, "ab12-cd34-ef56-7890"),
+                clear_after,
+                now,
+            )
+            .unwrap();
+
+        assert!(!guard.clear_if_unchanged(&clipboard, first).unwrap());
+        assert!(clipboard.read_text().unwrap().is_some());
+        assert!(guard.clear_if_unchanged(&clipboard, second).unwrap());
+    }
+
+    #[test]
+    fn test_clear_now_clears_the_latest_copy_early() {
+        let temp_dir = TempDir::new().unwrap();
+        let guard = guard(&temp_dir);
+        let clipboard = FakeClipboard::default();
+        assert!(!guard.is_guarding());
+        assert!(!guard.clear_now(&clipboard).unwrap());
+
+        let generation = guard
+            .copy(
+                &clipboard,
+                copy_of(ClipboardKind::RecoveryCode, SHARE),
+                DurationMs::from_secs(30),
+                Utc::now(),
+            )
+            .unwrap();
+        assert!(guard.is_guarding());
+
+        assert!(guard.clear_now(&clipboard).unwrap());
+        assert_eq!(clipboard.read_text().unwrap(), None);
+        assert!(!guard.is_guarding());
+
+        // The scheduled clear then finds nothing to do
+        assert!(!guard.clear_if_unchanged(&clipboard, generation).unwrap());
+    }
+
+    #[test]
+    fn test_recovery_codes_need_the_confirmation() {
+        let enabled = ClipboardSettings {
+            enabled: true,
+            ..ClipboardSettings::default()
+        };
+
+        assert!(
+            ClipboardGuard::check_allowed(&enabled, ClipboardKind::RecoveryCode, None).is_err()
+        );
+        assert!(
+            ClipboardGuard::check_allowed(&enabled, ClipboardKind::RecoveryCode, Some("copy"))
+                .is_err()
+        );
+        assert!(
+            ClipboardGuard::check_allowed(
+                &enabled,
+                ClipboardKind::RecoveryCode,
+                Some(COPY_RECOVERY_CODE_CONFIRMATION)
+            )
+            .is_ok()
+        );
+        assert!(ClipboardGuard::check_allowed(&enabled, ClipboardKind::Recipient, None).is_ok());
+
+        // Copying is opt-in
+        let err = ClipboardGuard::check_allowed(
+            &ClipboardSettings::default(),
+            ClipboardKind::Recipient,
+            None,
+        )
+        .unwrap_err();
+        assert!(matches!(err, VaultError::InvalidOperation(_)));
+    }
+
+    #[test]
+    fn test_audit_entry_is_redacted() {
+        let temp_dir = TempDir::new().unwrap();
+        let guard = guard(&temp_dir);
+        let clipboard = FakeClipboard::default();
+
+        guard
+            .copy(
+                &clipboard,
+                ClipboardCopy {
+                    kind: ClipboardKind::RecoveryCode,
+                    value_ref: "2",
+                    log_scope: "vault-001",
+                    value: SHARE,
+                },
+                DurationMs::from_secs(30),
+                Utc::now(),
+            )
+            .unwrap();
+
+        let log = OperationLog::load_from(&temp_dir.path().join("operation_log.json")).unwrap();
+        assert_eq!(log.entries.len(), 1);
+        let entry = &log.entries[0];
+        assert_eq!(entry.operation, LoggedOperation::ClipboardCopy);
+        assert_eq!(entry.vault_id, "vault-001");
+        assert!(entry.summary.contains("recovery code 2"));
+        assert!(!entry.summary.contains(SHARE));
+        assert!(!entry.summary.contains("Zx8P"));
+    }
+}
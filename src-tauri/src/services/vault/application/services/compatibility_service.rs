@@ -0,0 +1,52 @@
+//! Compatibility Service
+//!
+//! Records the app version on each start and lists the vault-format changes
+//! since the version that ran before, from the changelog embedded in the
+//! binary.
+
+use crate::prelude::*;
+use crate::services::vault::domain::models::{AppVersion, CompatibilityReport};
+use crate::services::vault::domain::{VaultError, VaultResult};
+use crate::services::vault::infrastructure::persistence::AppConfig;
+
+/// Service for the compatibility changelog
+#[derive(Debug, Default)]
+pub struct CompatibilityService;
+
+impl CompatibilityService {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Record this start, returning the changes since the previous version
+    /// when this is the first start after an upgrade
+    pub fn record_start(&self) -> VaultResult<Option<CompatibilityReport>> {
+        let mut config = load_config()?;
+        let current = AppVersion::current();
+        let upgraded_from = config.record_run(current);
+        config
+            .save()
+            .map_err(|e| VaultError::StorageError(format!("Failed to save app config: {e}")))?;
+
+        if let Some(previous) = upgraded_from {
+            info!(from = %previous, to = %current, "App upgraded");
+        }
+        Ok(upgraded_from.map(|previous| CompatibilityReport::new(Some(previous), current)))
+    }
+
+    /// Changes after `since`, defaulting to the version that ran before this one
+    pub fn get_changes(&self, since: Option<&str>) -> VaultResult<CompatibilityReport> {
+        let since = match since {
+            Some(text) => Some(AppVersion::parse(text).ok_or_else(|| {
+                VaultError::InvalidOperation(format!("Invalid app version: {text}"))
+            })?),
+            None => load_config()?.upgraded_from(),
+        };
+        Ok(CompatibilityReport::new(since, AppVersion::current()))
+    }
+}
+
+fn load_config() -> VaultResult<AppConfig> {
+    AppConfig::load()
+        .map_err(|e| VaultError::StorageError(format!("Failed to load app config: {e}")))
+}
@@ -0,0 +1,280 @@
+//! Dead Man's Switch Service
+//!
+//! Stores each vault's check-in schedule in the local vault settings and
+//! reports where it stands. Checking in needs the typed confirmation, so
+//! someone else using this machine can't push the deadline back by accident.
+//!
+//! Deadlines are judged on the clock. While it's unreliable the state is
+//! reported as unknown rather than lapsed, and check-ins are refused so a
+//! wrong clock can't record a check-in time that later reads as long overdue.
+
+use crate::prelude::*;
+use crate::services::shared::infrastructure::ClockService;
+use crate::services::vault::domain::models::{
+    CHECK_IN_CONFIRMATION, DeadMansSwitch, DeadMansSwitchInput, DeadMansSwitchStatus,
+};
+use crate::services::vault::domain::{VaultError, VaultResult};
+use crate::services::vault::infrastructure::persistence::VaultSettingsRegistry;
+use chrono::{DateTime, Utc};
+
+/// Service for per-vault dead man's switches
+#[derive(Debug)]
+pub struct DeadMansSwitchService {
+    clock: ClockService,
+}
+
+impl DeadMansSwitchService {
+    pub fn new() -> Self {
+        Self::with_clock(ClockService::global().clone())
+    }
+
+    pub fn with_clock(clock: ClockService) -> Self {
+        Self { clock }
+    }
+
+    /// A vault's switch evaluated now (none if never set)
+    pub fn get_status(&self, vault_id: &str) -> VaultResult<Option<DeadMansSwitchStatus>> {
+        Ok(self.status(&load_settings()?, vault_id))
+    }
+
+    /// Set up or change a vault's switch, or remove it with `None`
+    pub fn set_switch(
+        &self,
+        vault_id: &str,
+        input: Option<DeadMansSwitchInput>,
+    ) -> VaultResult<Option<DeadMansSwitchStatus>> {
+        let mut settings = load_settings()?;
+        self.apply(&mut settings, vault_id, input)?;
+        save_settings(&settings)?;
+        Ok(self.status(&settings, vault_id))
+    }
+
+    /// Restart a vault's check-in interval once the typed confirmation matches
+    pub fn check_in(
+        &self,
+        vault_id: &str,
+        confirmation: Option<&str>,
+    ) -> VaultResult<DeadMansSwitchStatus> {
+        let mut settings = load_settings()?;
+        let status = self.record_check_in(&mut settings, vault_id, confirmation)?;
+        save_settings(&settings)?;
+        Ok(status)
+    }
+
+    /// Evaluate a vault's switch in `settings`
+    pub fn status(
+        &self,
+        settings: &VaultSettingsRegistry,
+        vault_id: &str,
+    ) -> Option<DeadMansSwitchStatus> {
+        let now = self.clock.is_reliable().then(|| self.clock.now());
+        settings
+            .vaults
+            .get(vault_id)?
+            .dead_mans_switch
+            .as_ref()
+            .map(|switch| switch.status(now))
+    }
+
+    /// Store `input` in `settings`, keeping the last check-in of an existing switch
+    pub fn apply(
+        &self,
+        settings: &mut VaultSettingsRegistry,
+        vault_id: &str,
+        input: Option<DeadMansSwitchInput>,
+    ) -> VaultResult<()> {
+        let Some(input) = input else {
+            settings.entry(vault_id).dead_mans_switch = None;
+            info!(vault_id, "Removed dead man's switch");
+            return Ok(());
+        };
+        input.validate().map_err(VaultError::InvalidOperation)?;
+
+        let vault_settings = settings.entry(vault_id);
+        let checked_in_at = match &vault_settings.dead_mans_switch {
+            Some(existing) => existing.last_check_in_at,
+            None => self.reliable_now()?,
+        };
+        vault_settings.dead_mans_switch = Some(DeadMansSwitch::new(input, checked_in_at));
+
+        info!(vault_id, "Updated dead man's switch");
+        Ok(())
+    }
+
+    /// Record a check-in in `settings`
+    pub fn record_check_in(
+        &self,
+        settings: &mut VaultSettingsRegistry,
+        vault_id: &str,
+        confirmation: Option<&str>,
+    ) -> VaultResult<DeadMansSwitchStatus> {
+        if confirmation != Some(CHECK_IN_CONFIRMATION) {
+            return Err(VaultError::InvalidOperation(format!(
+                "Type '{}' to confirm checking in",
+                CHECK_IN_CONFIRMATION
+            )));
+        }
+
+        let now = self.reliable_now()?;
+        let switch = settings
+            .vaults
+            .get_mut(vault_id)
+            .and_then(|s| s.dead_mans_switch.as_mut())
+            .ok_or_else(|| {
+                VaultError::InvalidOperation(
+                    "No dead man's switch is set for this vault".to_string(),
+                )
+            })?;
+        switch.last_check_in_at = now;
+
+        info!(vault_id, due_at = %switch.due_at(), "Checked in");
+        Ok(switch.status(Some(now)))
+    }
+
+    fn reliable_now(&self) -> VaultResult<DateTime<Utc>> {
+        if !self.clock.is_reliable() {
+            return Err(VaultError::InvalidOperation(
+                "The system clock looks wrong; correct it and try again".to_string(),
+            ));
+        }
+        Ok(self.clock.now())
+    }
+}
+
+impl Default for DeadMansSwitchService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn load_settings() -> VaultResult<VaultSettingsRegistry> {
+    VaultSettingsRegistry::load().map_err(|e| VaultError::StorageError(e.to_string()))
+}
+
+fn save_settings(settings: &VaultSettingsRegistry) -> VaultResult<()> {
+    settings
+        .save()
+        .map_err(|e| VaultError::StorageError(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::shared::infrastructure::Clock;
+    use crate::services::shared::infrastructure::clock::testing::{FakeClock, service};
+    use crate::services::vault::domain::models::CheckInState;
+    use std::collections::BTreeMap;
+    use std::time::Duration;
+
+    const DAY: Duration = Duration::from_secs(24 * 60 * 60);
+
+    fn input() -> DeadMansSwitchInput {
+        DeadMansSwitchInput {
+            check_in_interval_days: 30,
+            notify_before_days: 7,
+            lapsed_message: "Call the lawyer; the recovery kit is with them.".to_string(),
+            lapsed_message_translations: BTreeMap::new(),
+        }
+    }
+
+    fn setup() -> (FakeClock, DeadMansSwitchService, VaultSettingsRegistry) {
+        let clock = FakeClock::at("2026-03-01T00:00:00Z");
+        let switches = DeadMansSwitchService::with_clock(service(&clock));
+        let mut settings = VaultSettingsRegistry::default();
+        switches
+            .apply(&mut settings, "vault-001", Some(input()))
+            .unwrap();
+        (clock, switches, settings)
+    }
+
+    #[test]
+    fn test_approaching_then_lapsed() {
+        let (clock, switches, settings) = setup();
+        assert_eq!(
+            switches.status(&settings, "vault-001").unwrap().state,
+            CheckInState::Current
+        );
+
+        clock.advance(DAY * 23);
+        let status = switches.status(&settings, "vault-001").unwrap();
+        assert_eq!(status.state, CheckInState::DueSoon);
+        assert_eq!(status.days_until_due, Some(7));
+        assert_eq!(status.lapsed_message, None);
+
+        clock.advance(DAY * 19);
+        let status = switches.status(&settings, "vault-001").unwrap();
+        assert_eq!(status.state, CheckInState::Lapsed);
+        assert_eq!(status.days_overdue, Some(12));
+        assert_eq!(status.lapsed_message, Some(input().lapsed_message));
+
+        assert!(switches.status(&settings, "vault-002").is_none());
+    }
+
+    #[test]
+    fn test_check_in_resets_the_interval() {
+        let (clock, switches, mut settings) = setup();
+        clock.advance(DAY * 40);
+
+        // Without the typed confirmation nothing changes
+        for confirmation in [None, Some("check in")] {
+            assert!(
+                switches
+                    .record_check_in(&mut settings, "vault-001", confirmation)
+                    .is_err()
+            );
+        }
+        assert_eq!(
+            switches.status(&settings, "vault-001").unwrap().state,
+            CheckInState::Lapsed
+        );
+
+        let status = switches
+            .record_check_in(&mut settings, "vault-001", Some(CHECK_IN_CONFIRMATION))
+            .unwrap();
+        assert_eq!(status.state, CheckInState::Current);
+        assert_eq!(status.days_until_due, Some(30));
+        assert_eq!(status.last_check_in_at, clock.now());
+
+        // Changing the schedule keeps the check-in just recorded
+        let mut longer = input();
+        longer.check_in_interval_days = 60;
+        switches
+            .apply(&mut settings, "vault-001", Some(longer))
+            .unwrap();
+        let status = switches.status(&settings, "vault-001").unwrap();
+        assert_eq!(status.last_check_in_at, clock.now());
+        assert_eq!(status.days_until_due, Some(60));
+    }
+
+    #[test]
+    fn test_bad_clock_reports_unknown() {
+        let (clock, switches, mut settings) = setup();
+        clock.advance(DAY * 10);
+
+        // A clock reset to 1970 (or far ahead) is not evidence of a lapse
+        for wall in ["1970-01-01T00:00:00Z", "2040-01-01T00:00:00Z"] {
+            clock.set_wall(wall);
+            let status = switches.status(&settings, "vault-001").unwrap();
+            assert_eq!(status.state, CheckInState::Unknown);
+            assert_eq!(status.lapsed_message, None);
+            assert!(
+                switches
+                    .record_check_in(&mut settings, "vault-001", Some(CHECK_IN_CONFIRMATION))
+                    .is_err()
+            );
+        }
+    }
+
+    #[test]
+    fn test_check_in_without_switch_fails() {
+        let (_clock, switches, mut settings) = setup();
+        assert!(
+            switches
+                .record_check_in(&mut settings, "vault-002", Some(CHECK_IN_CONFIRMATION))
+                .is_err()
+        );
+
+        switches.apply(&mut settings, "vault-001", None).unwrap();
+        assert!(switches.status(&settings, "vault-001").is_none());
+    }
+}
@@ -0,0 +1,330 @@
+//! Directory Comparison Service
+//!
+//! Compares a local directory against a vault's manifest without decrypting
+//! anything: local files are hashed with the same resilient, parallel reader
+//! encryption uses, and matched against the SHA-256 recorded per file.
+//!
+//! Only the latest archive's file list is kept in the manifest, so an older
+//! archive can't be compared this way.
+
+use crate::logging::current_trace_id;
+use crate::logging::spawn_blocking;
+use crate::prelude::*;
+use crate::services::file::infrastructure::file_operations::utils::{
+    should_exclude_file, walk_directories,
+};
+use crate::services::file::infrastructure::file_operations::{
+    ResilientSource, ResilientSourceConfig,
+};
+use crate::services::shared::infrastructure::path_matches_any;
+use crate::services::shared::infrastructure::progress::update_global_progress;
+use crate::services::vault::application::services::{ArchiveService, VaultService};
+use crate::services::vault::domain::models::{
+    ComparisonBuckets, DirectoryComparison, DirectoryDifferences, LocalFileDigest,
+    ManifestFileDigest, OriginalFileDigest, UnreadableFile,
+};
+use crate::services::vault::domain::{VaultError, VaultResult};
+use crate::types::{IoPriority, ProgressDetails, ProgressUpdate};
+use chrono::Utc;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Local files found by a directory scan
+#[derive(Debug, Default)]
+struct DirectoryScan {
+    files: Vec<LocalFileDigest>,
+    /// Subdirectories that aren't excluded, relative to the root
+    directories: Vec<String>,
+    unreadable: Vec<UnreadableFile>,
+    excluded_count: usize,
+}
+
+/// Service comparing local directories against vault manifests
+#[derive(Debug)]
+pub struct DirectoryComparisonService {
+    vault_service: VaultService,
+    archive_service: ArchiveService,
+}
+
+impl DirectoryComparisonService {
+    pub fn new() -> Self {
+        Self {
+            vault_service: VaultService::new(),
+            archive_service: ArchiveService::new(),
+        }
+    }
+
+    /// Compare `directory` against the vault's latest file list
+    ///
+    /// The vault template's exclusion patterns apply on top of
+    /// `exclude_patterns`. Hashing progress is published under `operation_id`.
+    pub async fn compare(
+        &self,
+        vault_id: &str,
+        archive_id: Option<&str>,
+        directory: &Path,
+        exclude_patterns: &[String],
+        operation_id: &str,
+    ) -> VaultResult<DirectoryComparison> {
+        let metadata = self.vault_service.get_vault(vault_id).await?;
+        let archive_id = self
+            .archive_service
+            .resolve_latest_archive(&metadata, archive_id)?;
+
+        let mut patterns = exclude_patterns.to_vec();
+        if let Some(template) = &metadata.template {
+            patterns.extend(template.exclusion_patterns.iter().cloned());
+        }
+
+        let manifest: Vec<ManifestFileDigest> = metadata
+            .content
+            .files
+            .iter()
+            .map(|file| ManifestFileDigest {
+                path: file.path.clone(),
+                size: file.size,
+                sha256: file.sha256.clone(),
+                original: file.transform.as_ref().map(|t| OriginalFileDigest {
+                    path: file.source_path().to_string(),
+                    size: t.original_size,
+                    sha256: t.original_sha256.clone(),
+                }),
+            })
+            .collect();
+
+        // Only manifests that record directories can tell an empty one is missing
+        let manifest_directories: Option<Vec<String>> = metadata.records_directories().then(|| {
+            metadata
+                .content
+                .directories
+                .iter()
+                .map(|directory| directory.path.clone())
+                .filter(|path| !path_matches_any(&patterns, path))
+                .collect()
+        });
+
+        let scan = spawn_blocking({
+            let directory = directory.to_path_buf();
+            let operation_id = operation_id.to_string();
+            move || scan_directory(&directory, &patterns, &operation_id)
+        })
+        .await
+        .map_err(|e| VaultError::OperationFailed(format!("Directory scan aborted: {}", e)))??;
+
+        let buckets = ComparisonBuckets::compare(&scan.files, &manifest);
+        let summary = buckets.summary(scan.excluded_count, scan.unreadable.len());
+        let directories = manifest_directories
+            .map(|recorded| DirectoryDifferences::compare(&scan.directories, &recorded, &buckets));
+        let fully_covered = buckets.modified.is_empty()
+            && buckets.missing_from_vault.is_empty()
+            && scan.unreadable.is_empty()
+            && directories
+                .as_ref()
+                .is_none_or(|d| d.missing_from_vault.is_empty());
+
+        info!(
+            vault_id = %vault_id,
+            covered = summary.covered_count,
+            modified = summary.modified_count,
+            missing_from_vault = summary.missing_from_vault_count,
+            missing_locally = summary.missing_locally_count,
+            "Compared directory against vault"
+        );
+
+        Ok(DirectoryComparison {
+            vault_id: vault_id.to_string(),
+            archive_id,
+            encryption_revision: metadata.encryption_revision(),
+            directory: directory.display().to_string(),
+            covered: buckets.covered,
+            modified: buckets.modified,
+            missing_from_vault: buckets.missing_from_vault,
+            missing_locally: buckets.missing_locally,
+            unreadable: scan.unreadable,
+            summary,
+            directories,
+            fully_covered,
+        })
+    }
+}
+
+impl Default for DirectoryComparisonService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Walk `directory` and hash every file that isn't excluded
+fn scan_directory(
+    directory: &Path,
+    patterns: &[String],
+    operation_id: &str,
+) -> VaultResult<DirectoryScan> {
+    if !directory.is_dir() {
+        return Err(VaultError::InvalidOperation(format!(
+            "'{}' is not a directory",
+            directory.display()
+        )));
+    }
+
+    let mut scan = DirectoryScan::default();
+    let mut candidates: Vec<(PathBuf, String)> = Vec::new();
+    for entry in walkdir::WalkDir::new(directory).follow_links(false) {
+        let entry = match entry {
+            Ok(entry) => entry,
+            Err(e) => {
+                let path = e.path().unwrap_or(directory);
+                scan.unreadable.push(UnreadableFile {
+                    path: relative_path(directory, path),
+                    reason: e.to_string(),
+                });
+                continue;
+            }
+        };
+        if !entry.file_type().is_file() {
+            continue;
+        }
+
+        let relative = relative_path(directory, entry.path());
+        if should_exclude_file(entry.path()) || path_matches_any(patterns, &relative) {
+            scan.excluded_count += 1;
+            continue;
+        }
+        candidates.push((entry.into_path(), relative));
+    }
+
+    scan.directories = walk_directories(directory)
+        .map(|entry| relative_path(directory, entry.path()))
+        .filter(|relative| !path_matches_any(patterns, relative))
+        .collect();
+
+    let source = ResilientSource::new(ResilientSourceConfig::default());
+    let total = candidates.len();
+    let hashed = AtomicUsize::new(0);
+    let results = source.map_files(&candidates, |(path, relative)| {
+        let result = source.hash_file(path, relative);
+        let done = hashed.fetch_add(1, Ordering::Relaxed) + 1;
+        report_progress(operation_id, relative, done, total);
+        result
+    });
+
+    for ((_, relative), result) in candidates.into_iter().zip(results) {
+        match result {
+            Ok((size, sha256)) => scan.files.push(LocalFileDigest {
+                path: relative,
+                size,
+                sha256,
+            }),
+            Err(e) => scan.unreadable.push(UnreadableFile {
+                path: relative,
+                reason: e.to_string(),
+            }),
+        }
+    }
+
+    Ok(scan)
+}
+
+fn relative_path(root: &Path, path: &Path) -> String {
+    path.strip_prefix(root)
+        .unwrap_or(path)
+        .to_string_lossy()
+        .to_string()
+}
+
+fn report_progress(operation_id: &str, current_file: &str, done: usize, total: usize) {
+    update_global_progress(
+        operation_id,
+        ProgressUpdate {
+            operation_id: operation_id.to_string(),
+            progress: done as f32 / total.max(1) as f32,
+            message: format!("Hashing local files ({}/{})", done, total),
+            details: Some(ProgressDetails::ManifestOperation {
+                files_verified: done,
+                total_files: total,
+                current_file: current_file.to_string(),
+            }),
+            timestamp: Utc::now(),
+            estimated_time_remaining: None,
+            io_priority: IoPriority::Normal,
+            trace_id: current_trace_id(),
+        },
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::shared::infrastructure::progress::get_global_progress;
+    use crate::services::vault::domain::models::comparison_key;
+    use sha2::{Digest, Sha256};
+    use tempfile::TempDir;
+
+    fn manifest_entry(path: &str, content: &[u8]) -> ManifestFileDigest {
+        ManifestFileDigest {
+            path: path.to_string(),
+            size: content.len() as u64,
+            sha256: hex::encode(Sha256::digest(content)),
+            original: None,
+        }
+    }
+
+    fn write(dir: &Path, relative: &str, content: &[u8]) {
+        let path = dir.join(relative);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, content).unwrap();
+    }
+
+    #[test]
+    fn test_scan_and_compare_fills_every_bucket() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir = temp_dir.path();
+        write(dir, "docs/same.txt", b"unchanged");
+        write(dir, "docs/edited.txt", b"edited locally");
+        write(dir, "new.txt", b"not backed up");
+        // Stored decomposed on disk, recorded composed in the manifest
+        write(dir, "Cafe\u{301}.txt", b"menu");
+        write(dir, "cache/build.tmp", b"ignored");
+        write(dir, ".DS_Store", b"finder");
+        std::fs::create_dir_all(dir.join("empty/nested")).unwrap();
+        std::fs::create_dir_all(dir.join(".git/objects")).unwrap();
+
+        let manifest = [
+            manifest_entry("docs/same.txt", b"unchanged"),
+            manifest_entry(r"docs\edited.txt", b"original"),
+            manifest_entry("deleted.txt", b"only in vault"),
+            manifest_entry("Caf\u{e9}.txt", b"menu"),
+        ];
+
+        let operation_id = "directory_comparison_test";
+        let scan = scan_directory(dir, &["*.tmp".to_string()], operation_id).unwrap();
+        assert_eq!(scan.excluded_count, 2);
+        assert!(scan.unreadable.is_empty());
+        let mut directories: Vec<String> =
+            scan.directories.iter().map(|d| comparison_key(d)).collect();
+        directories.sort();
+        assert_eq!(directories, ["cache", "docs", "empty", "empty/nested"]);
+
+        let buckets = ComparisonBuckets::compare(&scan.files, &manifest);
+        let covered: Vec<String> = buckets
+            .covered
+            .iter()
+            .map(|f| comparison_key(&f.path))
+            .collect();
+        assert_eq!(covered, ["Caf\u{e9}.txt", "docs/same.txt"]);
+        assert_eq!(buckets.modified.len(), 1);
+        assert_eq!(comparison_key(&buckets.modified[0].path), "docs/edited.txt");
+        assert_eq!(buckets.missing_from_vault[0].path, "new.txt");
+        assert_eq!(buckets.missing_locally[0].path, "deleted.txt");
+
+        let progress = get_global_progress(operation_id).unwrap();
+        assert_eq!(progress.progress, 1.0);
+    }
+
+    #[test]
+    fn test_scan_rejects_missing_directory() {
+        let temp_dir = TempDir::new().unwrap();
+        let result = scan_directory(&temp_dir.path().join("absent"), &[], "missing_dir_test");
+        assert!(matches!(result, Err(VaultError::InvalidOperation(_))));
+    }
+}
@@ -0,0 +1,633 @@
+//! Eject Safety Service
+//!
+//! Finishes archives written to removable media so the drive can be pulled
+//! as soon as the app says so. After the bundles replace their targets,
+//! `finalize`:
+//!
+//! 1. fsyncs each bundle and its folder, so the rename is on the device too,
+//! 2. reads each bundle back in full and compares its SHA-256 with the hash
+//!    computed when it was written (skippable in the app config for huge
+//!    archives, with a warning),
+//! 3. fsyncs the sidecars written beside it,
+//! 4. and remembers the archive for `prepare_eject`.
+//!
+//! `prepare_eject` flushes every remembered archive on a volume again, with
+//! its sidecars, since upload checksums and parity can be written later.
+//! Volumes that are fixed, or whose type can't be determined, are left as
+//! they are.
+
+use super::shutdown_service::{OperationControl, ProcessOperations};
+use crate::prelude::*;
+use crate::services::file::infrastructure::file_operations::{
+    generate_external_manifest_path, generate_parity_path, generate_upload_metadata_path,
+};
+use crate::services::shared::infrastructure::io::{
+    DurableStorage, ReplacedFile, SystemDurableStorage, VolumeInfo, VolumeInfoService,
+};
+use crate::services::vault::domain::models::{
+    EjectPreparation, EjectReadiness, SidecarFlush, SidecarKind,
+};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard, OnceLock};
+
+/// Archives written to removable volumes, by volume mount point
+type TrackedVolumes = BTreeMap<PathBuf, TrackedVolume>;
+
+#[derive(Debug, Default)]
+struct TrackedVolume {
+    name: String,
+    /// Archive path → its `RECOVERY.txt`, when one was written
+    archives: BTreeMap<PathBuf, Option<PathBuf>>,
+}
+
+/// Process-wide, so archives written by one request are flushed by the next
+fn process_tracked() -> Arc<Mutex<TrackedVolumes>> {
+    static TRACKED: OnceLock<Arc<Mutex<TrackedVolumes>>> = OnceLock::new();
+    TRACKED.get_or_init(Arc::default).clone()
+}
+
+/// Service making archives on removable media safe to unplug
+pub struct EjectSafetyService {
+    volumes: VolumeInfoService,
+    storage: Arc<dyn DurableStorage>,
+    operations: Box<dyn OperationControl>,
+    tracked: Arc<Mutex<TrackedVolumes>>,
+}
+
+impl std::fmt::Debug for EjectSafetyService {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EjectSafetyService")
+            .field("volumes", &self.volumes)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Default for EjectSafetyService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl EjectSafetyService {
+    pub fn new() -> Self {
+        Self {
+            volumes: VolumeInfoService::new(),
+            storage: Arc::new(SystemDurableStorage),
+            operations: Box::new(ProcessOperations),
+            tracked: process_tracked(),
+        }
+    }
+
+    /// Service over the given volumes, disk and registry, remembering
+    /// archives apart from the rest of the process
+    pub fn with(
+        volumes: VolumeInfoService,
+        storage: Arc<dyn DurableStorage>,
+        operations: Box<dyn OperationControl>,
+    ) -> Self {
+        Self {
+            volumes,
+            storage,
+            operations,
+            tracked: Arc::default(),
+        }
+    }
+
+    fn lock_tracked(&self) -> MutexGuard<'_, TrackedVolumes> {
+        self.tracked.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Make just-written bundles on removable media durable and verified
+    ///
+    /// `written` are the bundles as committed, with the SHA-256 of what was
+    /// written. Failures don't undo the encryption; they're reported as
+    /// warnings and leave the drive not safe to eject.
+    pub fn finalize(
+        &self,
+        written: &[ReplacedFile],
+        recovery_file: Option<&Path>,
+        verify_read_back: bool,
+    ) -> EjectReadiness {
+        let mut readiness = EjectReadiness::not_removable();
+        let mut verified = true;
+
+        for file in written {
+            let Some(volume) = file
+                .path
+                .parent()
+                .and_then(|dir| self.volumes.removable_volume_for(dir))
+            else {
+                continue;
+            };
+            readiness
+                .volume_name
+                .get_or_insert_with(|| volume.name.clone());
+            let name = file_name(&file.path);
+
+            let mut problems = Vec::new();
+            if let Err(e) = self.storage.sync_file(&file.path) {
+                problems.push(format!(
+                    "{name} couldn't be flushed to {}: {e}",
+                    volume.name
+                ));
+            }
+            if let Some(dir) = file.path.parent()
+                && let Err(e) = self.storage.sync_dir(dir)
+            {
+                problems.push(format!(
+                    "The folder holding {name} couldn't be flushed: {e}"
+                ));
+            }
+
+            if !verify_read_back {
+                verified = false;
+                warn!(
+                    archive = %file.path.display(),
+                    "Read-back verification on removable media is turned off"
+                );
+                readiness.warnings.push(format!(
+                    "{name} was flushed but not read back, because read-back verification \
+                     is turned off in settings"
+                ));
+            } else {
+                let problem = match self.storage.read_back_sha256(&file.path) {
+                    Ok(sha256) if sha256 == file.sha256 => None,
+                    Ok(_) => Some(format!(
+                        "{name} reads back differently from what was written; \
+                         encrypt again before relying on it"
+                    )),
+                    Err(e) => Some(format!("{name} couldn't be read back: {e}")),
+                };
+                if let Some(problem) = problem {
+                    verified = false;
+                    problems.push(problem);
+                }
+            }
+
+            let sidecars = sidecars_of(&file.path, recovery_file);
+            problems.extend(
+                self.flush_sidecars(&sidecars)
+                    .into_iter()
+                    .filter_map(|flush| flush.error),
+            );
+
+            if !problems.is_empty() {
+                readiness.safe_to_eject = false;
+                for problem in &problems {
+                    warn!(
+                        archive = %file.path.display(),
+                        problem = %problem,
+                        "Bundle isn't safe to eject"
+                    );
+                }
+                readiness.warnings.extend(problems);
+            }
+            self.track(&volume, &file.path, recovery_file);
+        }
+
+        readiness.read_back_verified = readiness.volume_name.is_some() && verified;
+        if let Some(volume) = &readiness.volume_name {
+            info!(
+                volume = %volume,
+                safe_to_eject = readiness.safe_to_eject,
+                read_back_verified = readiness.read_back_verified,
+                "Finished bundles on removable media"
+            );
+        }
+        readiness
+    }
+
+    fn track(&self, volume: &VolumeInfo, archive: &Path, recovery_file: Option<&Path>) {
+        let mut tracked = self.lock_tracked();
+        let entry = tracked.entry(volume.root.clone()).or_default();
+        entry.name = volume.name.clone();
+        entry
+            .archives
+            .insert(archive.to_path_buf(), recovery_file.map(Path::to_path_buf));
+    }
+
+    /// Flush what the app wrote to `volume` and say whether it can be ejected
+    ///
+    /// `volume` is the volume's name or mount point. Archives that are gone
+    /// from it are forgotten. A volume the app wrote nothing to has nothing
+    /// to flush; it still isn't safe while an operation runs, since that
+    /// operation may be writing to it.
+    #[instrument(skip(self))]
+    pub fn prepare_eject(&self, volume: &str) -> EjectPreparation {
+        let (volume_name, archives) = {
+            let mut tracked = self.lock_tracked();
+            match tracked
+                .iter_mut()
+                .find(|(root, entry)| {
+                    entry.name.eq_ignore_ascii_case(volume) || root.as_path() == Path::new(volume)
+                })
+                .map(|(_, entry)| entry)
+            {
+                Some(entry) => {
+                    entry.archives.retain(|archive, _| archive.exists());
+                    (entry.name.clone(), entry.archives.clone())
+                }
+                None => (volume.to_string(), BTreeMap::new()),
+            }
+        };
+
+        let mut warnings = Vec::new();
+        let mut sidecars = Vec::new();
+        let mut archives_flushed = 0;
+        let mut dirs = BTreeSet::new();
+        for (archive, recovery_file) in &archives {
+            match self.storage.sync_file(archive) {
+                Ok(()) => archives_flushed += 1,
+                Err(e) => warnings.push(format!("{} couldn't be flushed: {e}", file_name(archive))),
+            }
+            sidecars.extend(self.flush_sidecars(&sidecars_of(archive, recovery_file.as_deref())));
+            if let Some(dir) = archive.parent() {
+                dirs.insert(dir.to_path_buf());
+            }
+        }
+        for dir in dirs {
+            if let Err(e) = self.storage.sync_dir(&dir) {
+                warnings.push(format!("{} couldn't be flushed: {e}", dir.display()));
+            }
+        }
+
+        let running_operations: Vec<_> = self
+            .operations
+            .running()
+            .into_iter()
+            .map(|operation| operation.kind)
+            .collect();
+        let preparation = EjectPreparation {
+            safe_to_eject: running_operations.is_empty()
+                && warnings.is_empty()
+                && sidecars.iter().all(|sidecar| sidecar.error.is_none()),
+            volume_name,
+            archives_flushed,
+            sidecars,
+            running_operations,
+            warnings,
+        };
+
+        info!(
+            volume = %preparation.volume_name,
+            archives = preparation.archives_flushed,
+            sidecars = preparation.sidecars.len(),
+            failed = preparation.failed().count(),
+            running = preparation.running_operations.len(),
+            safe_to_eject = preparation.safe_to_eject,
+            "Prepared volume for eject"
+        );
+        preparation
+    }
+
+    /// Flush the sidecars that exist; missing ones aren't an error
+    fn flush_sidecars(&self, sidecars: &[(SidecarKind, PathBuf)]) -> Vec<SidecarFlush> {
+        sidecars
+            .iter()
+            .filter(|(_, path)| path.is_file())
+            .map(|(kind, path)| SidecarFlush {
+                kind: *kind,
+                path: path.display().to_string(),
+                error: self
+                    .storage
+                    .sync_file(path)
+                    .err()
+                    .map(|e| format!("{} couldn't be flushed: {e}", file_name(path))),
+            })
+            .collect()
+    }
+}
+
+/// Files that may have been written beside `archive`
+fn sidecars_of(archive: &Path, recovery_file: Option<&Path>) -> Vec<(SidecarKind, PathBuf)> {
+    let mut sidecars = vec![
+        (
+            SidecarKind::ExternalManifest,
+            generate_external_manifest_path(archive),
+        ),
+        (
+            SidecarKind::UploadMetadata,
+            generate_upload_metadata_path(archive),
+        ),
+        (SidecarKind::Parity, generate_parity_path(archive)),
+    ];
+    if let Some(recovery_file) = recovery_file {
+        sidecars.push((
+            SidecarKind::RecoveryInstructions,
+            recovery_file.to_path_buf(),
+        ));
+    }
+    sidecars
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| path.display().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::shared::infrastructure::io::{VolumeKind, VolumeProbe};
+    use crate::services::shared::infrastructure::progress::{OperationKind, RunningOperation};
+    use crate::types::ProgressUpdate;
+    use chrono::Utc;
+    use sha2::{Digest, Sha256};
+    use std::collections::HashSet;
+    use std::fs;
+    use std::io;
+    use std::time::Duration;
+    use tempfile::TempDir;
+
+    /// Reports every path under `root` as one volume of `kind`
+    struct FixedVolume {
+        root: PathBuf,
+        kind: VolumeKind,
+    }
+
+    impl VolumeProbe for FixedVolume {
+        fn volume_for(&self, path: &Path) -> Option<VolumeInfo> {
+            path.starts_with(&self.root).then(|| VolumeInfo {
+                root: self.root.clone(),
+                name: "BACKUP STICK".to_string(),
+                kind: self.kind,
+            })
+        }
+    }
+
+    /// Detection that never works
+    struct NoVolumes;
+
+    impl VolumeProbe for NoVolumes {
+        fn volume_for(&self, _path: &Path) -> Option<VolumeInfo> {
+            None
+        }
+    }
+
+    /// A drive with a write cache
+    ///
+    /// Files written through `std::fs` count as cached until `sync_file`
+    /// flushes them. Reading back an unflushed file sees what a drive
+    /// pulled too early would hold: only the first half. A drive that
+    /// `drops_writes` acknowledges the flush but keeps the half.
+    #[derive(Default)]
+    struct WriteCacheSimulator {
+        flushed: Mutex<HashSet<PathBuf>>,
+        synced_dirs: Mutex<HashSet<PathBuf>>,
+        read_back: Mutex<Vec<PathBuf>>,
+        drops_writes: bool,
+        /// File names whose flush fails
+        failing: HashSet<String>,
+    }
+
+    impl WriteCacheSimulator {
+        fn flushed(&self, path: &Path) -> bool {
+            self.flushed.lock().unwrap().contains(path)
+        }
+    }
+
+    impl DurableStorage for WriteCacheSimulator {
+        fn sync_file(&self, path: &Path) -> io::Result<()> {
+            if self.failing.contains(&file_name(path)) {
+                return Err(io::Error::other("device not ready"));
+            }
+            self.flushed.lock().unwrap().insert(path.to_path_buf());
+            Ok(())
+        }
+
+        fn sync_dir(&self, dir: &Path) -> io::Result<()> {
+            self.synced_dirs.lock().unwrap().insert(dir.to_path_buf());
+            Ok(())
+        }
+
+        fn read_back_sha256(&self, path: &Path) -> io::Result<String> {
+            self.read_back.lock().unwrap().push(path.to_path_buf());
+            let data = fs::read(path)?;
+            let on_device = if self.flushed(path) && !self.drops_writes {
+                &data[..]
+            } else {
+                &data[..data.len() / 2]
+            };
+            Ok(hex::encode(Sha256::digest(on_device)))
+        }
+    }
+
+    /// Registry with `running` operations that never end
+    struct Registry(Vec<OperationKind>);
+
+    impl OperationControl for Registry {
+        fn running(&self) -> Vec<RunningOperation> {
+            self.0
+                .iter()
+                .map(|kind| RunningOperation {
+                    kind: *kind,
+                    vault_id: None,
+                    progress_id: None,
+                    started_at: Utc::now(),
+                })
+                .collect()
+        }
+
+        fn progress(&self, _progress_id: &str) -> Option<ProgressUpdate> {
+            None
+        }
+
+        fn cancel(&self) -> Vec<OperationKind> {
+            Vec::new()
+        }
+
+        fn wait_until_idle(&self, _timeout: Duration) -> bool {
+            true
+        }
+    }
+
+    struct Fixture {
+        dir: TempDir,
+        storage: Arc<WriteCacheSimulator>,
+        service: EjectSafetyService,
+    }
+
+    impl Fixture {
+        /// Temp folder on a volume of `kind`, or on one that can't be
+        /// detected when `None`
+        fn build(
+            kind: Option<VolumeKind>,
+            storage: WriteCacheSimulator,
+            running: Vec<OperationKind>,
+        ) -> Self {
+            let dir = TempDir::new().unwrap();
+            let volumes = match kind {
+                Some(kind) => VolumeInfoService::with_probe(FixedVolume {
+                    root: dir.path().to_path_buf(),
+                    kind,
+                }),
+                None => VolumeInfoService::with_probe(NoVolumes),
+            };
+            let storage = Arc::new(storage);
+            let service =
+                EjectSafetyService::with(volumes, storage.clone(), Box::new(Registry(running)));
+            Self {
+                dir,
+                storage,
+                service,
+            }
+        }
+
+        fn removable(storage: WriteCacheSimulator) -> Self {
+            Self::build(Some(VolumeKind::Removable), storage, Vec::new())
+        }
+
+        fn write(&self, name: &str, data: &[u8]) -> ReplacedFile {
+            let path = self.dir.path().join(name);
+            fs::write(&path, data).unwrap();
+            ReplacedFile {
+                path,
+                size: data.len() as u64,
+                sha256: hex::encode(Sha256::digest(data)),
+                replaced_existing: false,
+                previous_sha256: None,
+            }
+        }
+    }
+
+    #[test]
+    fn test_finalize_flushes_before_reading_back() {
+        let fixture = Fixture::removable(WriteCacheSimulator::default());
+        let backup = fixture.write("Estate.age", &[7u8; 4096]);
+        let shared = fixture.write("Estate-shared.age", &[9u8; 2048]);
+        let recovery = fixture.dir.path().join("Estate-RECOVERY.txt");
+        fs::write(&recovery, "instructions").unwrap();
+
+        let readiness =
+            fixture
+                .service
+                .finalize(&[backup.clone(), shared.clone()], Some(&recovery), true);
+
+        assert_eq!(readiness.volume_name.as_deref(), Some("BACKUP STICK"));
+        assert!(readiness.safe_to_eject, "{:?}", readiness.warnings);
+        assert!(readiness.read_back_verified);
+        assert!(fixture.storage.flushed(&backup.path));
+        assert!(fixture.storage.flushed(&shared.path));
+        assert!(fixture.storage.flushed(&recovery));
+        assert!(
+            fixture
+                .storage
+                .synced_dirs
+                .lock()
+                .unwrap()
+                .contains(fixture.dir.path())
+        );
+        assert_eq!(fixture.storage.read_back.lock().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_lost_writes_fail_read_back() {
+        let fixture = Fixture::removable(WriteCacheSimulator {
+            drops_writes: true,
+            ..Default::default()
+        });
+        let backup = fixture.write("Estate.age", &[7u8; 4096]);
+
+        let readiness = fixture.service.finalize(&[backup], None, true);
+
+        assert!(!readiness.safe_to_eject);
+        assert!(!readiness.read_back_verified);
+        assert!(readiness.warnings[0].contains("reads back differently"));
+    }
+
+    #[test]
+    fn test_skipped_read_back_records_warning() {
+        let fixture = Fixture::removable(WriteCacheSimulator::default());
+        let backup = fixture.write("Estate.age", &[7u8; 4096]);
+
+        let readiness = fixture.service.finalize(&[backup.clone()], None, false);
+
+        assert!(readiness.safe_to_eject);
+        assert!(!readiness.read_back_verified);
+        assert!(fixture.storage.flushed(&backup.path));
+        assert!(fixture.storage.read_back.lock().unwrap().is_empty());
+        assert_eq!(readiness.warnings.len(), 1);
+        assert!(readiness.warnings[0].contains("not read back"));
+    }
+
+    #[test]
+    fn test_undetected_volume_behaves_like_fixed_disk() {
+        for kind in [None, Some(VolumeKind::Unknown), Some(VolumeKind::Fixed)] {
+            let fixture = Fixture::build(kind, WriteCacheSimulator::default(), Vec::new());
+            let backup = fixture.write("Estate.age", &[7u8; 4096]);
+
+            let readiness = fixture.service.finalize(&[backup.clone()], None, true);
+
+            assert_eq!(readiness, EjectReadiness::not_removable());
+            assert!(!fixture.storage.flushed(&backup.path));
+            assert!(fixture.storage.read_back.lock().unwrap().is_empty());
+            let preparation = fixture.service.prepare_eject("BACKUP STICK");
+            assert_eq!(preparation.archives_flushed, 0);
+            assert!(preparation.safe_to_eject);
+        }
+    }
+
+    #[test]
+    fn test_prepare_eject_accounts_for_sidecars() {
+        let fixture = Fixture::removable(WriteCacheSimulator {
+            failing: HashSet::from(["Notes.age.upload.json".to_string()]),
+            ..Default::default()
+        });
+        let estate = fixture.write("Estate.age", b"estate archive");
+        let notes = fixture.write("Notes.age", b"notes archive");
+        let recovery = fixture.dir.path().join("Estate-RECOVERY.txt");
+        fs::write(&recovery, "instructions").unwrap();
+        fixture.service.finalize(&[estate], Some(&recovery), true);
+        fixture.service.finalize(&[notes], None, true);
+
+        // Written after encryption, e.g. by the upload and parity commands
+        for name in [
+            "Estate.manifest",
+            "Estate.age.upload.json",
+            "Estate.age.par",
+            "Notes.age.par",
+            "Notes.age.upload.json",
+        ] {
+            fs::write(fixture.dir.path().join(name), name).unwrap();
+        }
+
+        let preparation = fixture.service.prepare_eject("backup stick");
+
+        assert_eq!(preparation.volume_name, "BACKUP STICK");
+        assert_eq!(preparation.archives_flushed, 2);
+        assert_eq!(preparation.sidecars.len(), 6);
+        assert_eq!(preparation.flushed(SidecarKind::ExternalManifest), 1);
+        assert_eq!(preparation.flushed(SidecarKind::RecoveryInstructions), 1);
+        assert_eq!(preparation.flushed(SidecarKind::Parity), 2);
+        assert_eq!(preparation.flushed(SidecarKind::UploadMetadata), 1);
+        let failed: Vec<_> = preparation.failed().collect();
+        assert_eq!(failed.len(), 1);
+        assert!(failed[0].path.ends_with("Notes.age.upload.json"));
+        assert!(!preparation.safe_to_eject);
+
+        // Archives removed from the volume are forgotten
+        fs::remove_file(fixture.dir.path().join("Notes.age")).unwrap();
+        let preparation = fixture.service.prepare_eject("BACKUP STICK");
+        assert_eq!(preparation.archives_flushed, 1);
+        assert!(preparation.safe_to_eject);
+    }
+
+    #[test]
+    fn test_running_operation_blocks_eject() {
+        let fixture = Fixture::build(
+            None,
+            WriteCacheSimulator::default(),
+            vec![OperationKind::Encryption],
+        );
+
+        let preparation = fixture.service.prepare_eject("BACKUP STICK");
+
+        assert_eq!(
+            preparation.running_operations,
+            vec![OperationKind::Encryption]
+        );
+        assert!(!preparation.safe_to_eject);
+    }
+}
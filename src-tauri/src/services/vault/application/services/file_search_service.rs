@@ -0,0 +1,351 @@
+//! File Search Service
+//!
+//! Keeps the file search index in step with the archive index and answers
+//! "which archive contains this file?" across vaults. The index is updated
+//! whenever an archive is recorded, quarantined, or has its comment edited,
+//! and when a vault is deleted. Updates never fail the operation that
+//! triggered them: a missing or unreadable index is left alone and rebuilt
+//! on the next search.
+//!
+//! Rebuilds read the archive index and each vault's manifest, preferring the
+//! external manifest beside the archive when it is at least as new. Manifests
+//! only hold the latest encryption's file list, so after a rebuild older
+//! archives are searchable by comment only.
+//!
+//! File names are user content: log query lengths and counts, never text.
+
+use crate::prelude::*;
+use crate::services::vault::application::services::VaultMetadataService;
+use crate::services::vault::domain::models::{
+    ArchiveIndexEntry, FileQuery, FileSearchResults, FileSearchScope, IndexedArchive, IndexedFile,
+    search_indexed_files,
+};
+use crate::services::vault::domain::{VaultError, VaultResult};
+use crate::services::vault::infrastructure::persistence::{
+    ArchiveIndex, FileSearchIndex, VaultMetadata, list_vaults,
+};
+use std::path::{Path, PathBuf};
+
+/// Service for the cross-archive file search index
+#[derive(Debug)]
+pub struct FileSearchService {
+    metadata_service: VaultMetadataService,
+}
+
+impl FileSearchService {
+    pub fn new() -> Self {
+        Self {
+            metadata_service: VaultMetadataService::new(),
+        }
+    }
+
+    /// Index a just-recorded archive with its manifest's file list
+    pub fn index_archive(&self, manifest: &VaultMetadata, entry: &ArchiveIndexEntry) {
+        update("index_archive", |index| {
+            index.upsert(indexed_archive(manifest, entry));
+        });
+    }
+
+    /// Drop archives removed from the archive index
+    pub fn remove_archives(&self, vault_id: &str, archive_ids: &[String]) {
+        if archive_ids.is_empty() {
+            return;
+        }
+        update("remove_archives", |index| {
+            index.remove_archives(vault_id, archive_ids);
+        });
+    }
+
+    /// Drop everything indexed for a deleted vault
+    pub fn remove_vault(&self, vault_id: &str) {
+        update("remove_vault", |index| {
+            index.remove_vault(vault_id);
+        });
+    }
+
+    /// Follow an archive comment edit
+    pub fn update_comment(&self, entry: &ArchiveIndexEntry) {
+        update("update_comment", |index| {
+            index.set_comment(&entry.vault_id, &entry.archive_id, entry.comment.clone());
+        });
+    }
+
+    /// Files matching `query` in the vaults `scope` covers, grouped by vault
+    /// and archive
+    pub async fn search(
+        &self,
+        query: &str,
+        scope: &FileSearchScope,
+    ) -> VaultResult<FileSearchResults> {
+        let parsed = FileQuery::parse(query)?;
+        let path = index_path()?;
+        let index = match FileSearchIndex::load_from(&path) {
+            Some(index) => index,
+            None => {
+                let archive_index =
+                    ArchiveIndex::load().map_err(|e| VaultError::StorageError(e.to_string()))?;
+                let vaults = list_vaults()
+                    .await
+                    .map_err(|e| VaultError::StorageError(e.to_string()))?;
+                let manifests: Vec<_> = vaults
+                    .into_iter()
+                    .map(|vault| self.newest_manifest(vault))
+                    .collect();
+                Self::rebuild_at(&path, &archive_index, &manifests)
+            }
+        };
+
+        let results = search_indexed_files(
+            index
+                .archives()
+                .filter(|archive| scope.includes(&archive.vault_id)),
+            &parsed,
+        );
+        debug!(
+            query_len = query.chars().count(),
+            match_count = results.total_matches,
+            truncated = results.truncated,
+            "Searched file index"
+        );
+        Ok(results)
+    }
+
+    /// Build the index from the archive index and manifests and save it to
+    /// `path`
+    ///
+    /// A failed save is logged; the built index is still returned.
+    pub fn rebuild_at(
+        path: &Path,
+        archive_index: &ArchiveIndex,
+        manifests: &[VaultMetadata],
+    ) -> FileSearchIndex {
+        let index = Self::build(archive_index, manifests);
+        match index.save_to(path) {
+            Ok(()) => info!(
+                archive_count = index.archives().count(),
+                "Rebuilt file search index"
+            ),
+            Err(e) => warn!(error = %e, "Couldn't save rebuilt file search index"),
+        }
+        index
+    }
+
+    /// Index every recorded archive of the given vaults
+    ///
+    /// The latest archive matching a manifest's revision gets its file list;
+    /// other archives are indexed by comment only.
+    pub fn build(archive_index: &ArchiveIndex, manifests: &[VaultMetadata]) -> FileSearchIndex {
+        let mut index = FileSearchIndex::default();
+        for manifest in manifests {
+            let entries = archive_index.entries(manifest.vault_id());
+            let latest = entries
+                .iter()
+                .rev()
+                .find(|entry| entry.encryption_revision == manifest.encryption_revision())
+                .map(|entry| entry.archive_id.as_str());
+
+            for entry in entries {
+                let mut archive = indexed_archive(manifest, entry);
+                if latest != Some(entry.archive_id.as_str()) {
+                    archive.files.clear();
+                }
+                index.upsert(archive);
+            }
+        }
+        index
+    }
+
+    /// The external manifest when it is at least as new as the stored copy
+    fn newest_manifest(&self, stored: VaultMetadata) -> VaultMetadata {
+        match self
+            .metadata_service
+            .load_saved(&stored.vault.sanitized_name)
+        {
+            Ok(Some(external))
+                if external.encryption_revision() >= stored.encryption_revision() =>
+            {
+                external
+            }
+            _ => stored,
+        }
+    }
+}
+
+impl Default for FileSearchService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Apply `change` to the saved index
+///
+/// Skipped when there is no readable index yet; the next search rebuilds it
+/// with the change included.
+fn update(operation: &str, change: impl FnOnce(&mut FileSearchIndex)) {
+    match index_path() {
+        Ok(path) => update_at(&path, change),
+        Err(e) => warn!(operation, error = %e, "Couldn't update file search index"),
+    }
+}
+
+fn update_at(path: &Path, change: impl FnOnce(&mut FileSearchIndex)) {
+    let Some(mut index) = FileSearchIndex::load_from(path) else {
+        debug!("No file search index to update; it will be rebuilt");
+        return;
+    };
+    change(&mut index);
+    if let Err(e) = index.save_to(path) {
+        // A stale index would hide the change; drop it so the next search rebuilds
+        warn!(error = %e, "Couldn't save file search index");
+        let _ = std::fs::remove_file(path);
+    }
+}
+
+fn index_path() -> VaultResult<PathBuf> {
+    FileSearchIndex::get_index_path().map_err(|e| VaultError::StorageError(e.to_string()))
+}
+
+fn indexed_archive(manifest: &VaultMetadata, entry: &ArchiveIndexEntry) -> IndexedArchive {
+    IndexedArchive {
+        vault_id: entry.vault_id.clone(),
+        vault_name: manifest.label().to_string(),
+        archive_id: entry.archive_id.clone(),
+        archive_name: entry.archive_name.clone(),
+        created_at: entry.created_at,
+        comment: entry.comment.clone(),
+        files: manifest
+            .content
+            .files
+            .iter()
+            .map(|file| IndexedFile {
+                path: file.path.replace('\\', "/"),
+                size: file.size,
+                content_type: file.content_type.clone(),
+            })
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::file::infrastructure::file_operations::{HashAlgorithm, RawPath};
+    use crate::services::shared::infrastructure::DeviceInfo;
+    use crate::services::vault::application::services::ArchiveService;
+    use crate::services::vault::infrastructure::persistence::metadata::VaultFileEntry;
+    use chrono::Utc;
+    use tempfile::TempDir;
+
+    fn manifest(files: &[&str]) -> VaultMetadata {
+        let device_info = DeviceInfo {
+            machine_id: "machine-1".to_string(),
+            machine_label: "laptop".to_string(),
+            created_at: Utc::now(),
+            app_version: "2.0.0".to_string(),
+        };
+        let files = files
+            .iter()
+            .map(|path| VaultFileEntry {
+                path: path.to_string(),
+                raw_path: RawPath::from_display(path),
+                lossy_name: false,
+                size: 1024,
+                sha256: String::new(),
+                hash_algorithm: HashAlgorithm::Sha256,
+                digest: None,
+                ownership: None,
+                content_type: None,
+                content_type_mismatch: false,
+                transform: None,
+            })
+            .collect();
+        VaultMetadata::new(
+            "vault-001".to_string(),
+            "Family".to_string(),
+            None,
+            "Family".to_string(),
+            &device_info,
+            None,
+            vec![],
+            files,
+            0,
+            0,
+        )
+    }
+
+    fn search(path: &Path, query: &str) -> Vec<String> {
+        let index = FileSearchIndex::load_from(path).unwrap();
+        search_indexed_files(index.archives(), &FileQuery::parse(query).unwrap())
+            .vaults
+            .into_iter()
+            .flat_map(|vault| vault.archives)
+            .map(|archive| archive.archive_id)
+            .collect()
+    }
+
+    #[test]
+    fn test_incremental_updates_follow_prune_and_comment() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("file_search_index.json");
+        FileSearchIndex::default().save_to(&path).unwrap();
+
+        let mut archive_index = ArchiveIndex::default();
+        let manifest = manifest(&["taxes/tax-2019.pdf"]);
+        let first = ArchiveService::record_in(&mut archive_index, &manifest, "Family.age");
+        let second = ArchiveService::record_in(&mut archive_index, &manifest, "Family.age");
+        for entry in [&first, &second] {
+            update_at(&path, |index| {
+                index.upsert(indexed_archive(&manifest, entry))
+            });
+        }
+        assert_eq!(search(&path, "tax-2019").len(), 2);
+
+        // Quarantine drops the first archive from the archive index
+        let pruned = vec![first.archive_id.clone()];
+        update_at(&path, |index| {
+            index.remove_archives("vault-001", &pruned);
+        });
+        assert_eq!(search(&path, "tax-2019"), vec![second.archive_id.clone()]);
+
+        update_at(&path, |index| {
+            index.set_comment("vault-001", &second.archive_id, Some("Scans".to_string()));
+        });
+        assert_eq!(search(&path, "scans ext:pdf"), vec![second.archive_id]);
+    }
+
+    #[test]
+    fn test_updates_skip_missing_index() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("file_search_index.json");
+
+        update_at(&path, |index| {
+            index.remove_vault("vault-001");
+        });
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_corrupt_index_is_rebuilt() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("file_search_index.json");
+        std::fs::write(&path, b"not json").unwrap();
+        assert!(FileSearchIndex::load_from(&path).is_none());
+
+        let mut archive_index = ArchiveIndex::default();
+        let mut older = manifest(&["old.txt"]);
+        let first = ArchiveService::record_in(&mut archive_index, &older, "Family.age");
+        older.versioning.revision += 1;
+        older.content.files[0].path = "photos\\beach.jpg".to_string();
+        let latest = ArchiveService::record_in(&mut archive_index, &older, "Family.age");
+
+        let rebuilt = FileSearchService::rebuild_at(&path, &archive_index, &[older]);
+        assert_eq!(rebuilt.archives().count(), 2);
+        assert_eq!(FileSearchIndex::load_from(&path), Some(rebuilt));
+
+        // Only the latest archive's file list is known after a rebuild
+        assert_eq!(search(&path, "photos/beach"), vec![latest.archive_id]);
+        assert!(search(&path, "old.txt").is_empty());
+        let index = FileSearchIndex::load_from(&path).unwrap();
+        assert!(index.archives().any(|a| a.archive_id == first.archive_id));
+    }
+}
@@ -0,0 +1,166 @@
+//! Post-operation Hook Service
+//!
+//! Stores each vault's hooks in the local vault settings (validated on save)
+//! and runs the hooks matching an operation's outcome. Hooks run on their own
+//! thread so a slow script never holds up the operation; the caller waits at
+//! most the vault's response grace period. Every run is recorded in the
+//! operation log with its exit code and truncated output.
+
+use crate::logging::in_current_trace;
+use crate::prelude::*;
+use crate::services::vault::application::services::OperationLogService;
+use crate::services::vault::domain::models::{
+    HookContext, HookDefinition, VaultHooks, validate_hooks,
+};
+use crate::services::vault::domain::{VaultError, VaultResult};
+use crate::services::vault::infrastructure::hook_runner::{HookRunOutcome, run_hook};
+use crate::services::vault::infrastructure::persistence::{
+    LoggedOperation, OperationLogEntry, VaultSettingsRegistry,
+};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Archive path passed to a hook under test
+pub const TEST_HOOK_ARCHIVE_PATH: &str = "/tmp/barqly-hook-test.age";
+
+/// How often the caller checks whether detached hooks have finished
+const GRACE_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Service for per-vault post-operation hooks
+pub struct HookService;
+
+impl HookService {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// A vault's hooks (none if never configured)
+    pub fn get_hooks(&self, vault_id: &str) -> VaultResult<VaultHooks> {
+        Ok(load_settings()?.get(vault_id).hooks)
+    }
+
+    /// Replace a vault's hooks after validating every definition
+    pub fn update_hooks(&self, vault_id: &str, hooks: VaultHooks) -> VaultResult<VaultHooks> {
+        validate_hooks(&hooks).map_err(VaultError::InvalidOperation)?;
+
+        let mut settings = load_settings()?;
+        settings.entry(vault_id).hooks = hooks.clone();
+        save_settings(&settings)?;
+
+        info!(
+            vault_id,
+            hook_count = hooks.hooks.len(),
+            "Updated vault hooks"
+        );
+        Ok(hooks)
+    }
+
+    /// Run one hook now with placeholder values, waiting for it to finish
+    pub fn test_hook(
+        &self,
+        vault_id: &str,
+        vault_name: &str,
+        hook_id: &str,
+    ) -> VaultResult<HookRunOutcome> {
+        let hooks = self.get_hooks(vault_id)?;
+        let hook = hooks
+            .hooks
+            .iter()
+            .find(|hook| hook.id == hook_id)
+            .ok_or_else(|| {
+                VaultError::InvalidOperation(format!("Hook '{}' is not configured", hook_id))
+            })?;
+
+        let context = HookContext {
+            event: hook.event,
+            vault_id: vault_id.to_string(),
+            vault_name: vault_name.to_string(),
+            archive_path: TEST_HOOK_ARCHIVE_PATH.to_string(),
+            status: "test".to_string(),
+        };
+        Ok(run_and_record(hook, &context))
+    }
+
+    /// Run the vault's enabled hooks for `context.event`
+    ///
+    /// Returns once the hooks finish or the vault's response grace period
+    /// passes, whichever is first; hooks still running carry on detached.
+    /// Never fails: hook problems end up in the operation log.
+    pub async fn dispatch(&self, context: HookContext) {
+        let hooks = match self.get_hooks(&context.vault_id) {
+            Ok(hooks) => hooks,
+            Err(e) => {
+                warn!(error = %e, "Failed to load vault hooks");
+                return;
+            }
+        };
+        let matching: Vec<HookDefinition> = hooks.for_event(context.event).cloned().collect();
+        if matching.is_empty() {
+            return;
+        }
+
+        debug!(
+            vault_id = %context.vault_id,
+            event = context.event.as_str(),
+            hook_count = matching.len(),
+            "Running vault hooks"
+        );
+        let (done, finished) = mpsc::channel();
+        thread::spawn(in_current_trace(move || {
+            for hook in &matching {
+                run_and_record(hook, &context);
+            }
+            let _ = done.send(());
+        }));
+
+        let grace = Duration::from_secs(u64::from(hooks.response_grace_secs));
+        let deadline = Instant::now() + grace;
+        while Instant::now() < deadline {
+            match finished.try_recv() {
+                Err(mpsc::TryRecvError::Empty) => tokio::time::sleep(GRACE_POLL_INTERVAL).await,
+                _ => break,
+            }
+        }
+    }
+}
+
+impl Default for HookService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn run_and_record(hook: &HookDefinition, context: &HookContext) -> HookRunOutcome {
+    let outcome = run_hook(hook, context);
+    if outcome.succeeded() {
+        info!(hook_id = %hook.id, duration_ms = outcome.duration_ms, "Vault hook finished");
+    } else {
+        warn!(
+            hook_id = %hook.id,
+            exit_code = ?outcome.exit_code,
+            timed_out = outcome.timed_out,
+            "Vault hook failed"
+        );
+    }
+
+    let entry = OperationLogEntry::new(
+        LoggedOperation::HookRun,
+        &context.vault_id,
+        outcome.summary(context),
+    );
+    if let Err(e) = OperationLogService::new().record(entry) {
+        warn!(error = %e, "Failed to record hook run in the operation log");
+    }
+    outcome
+}
+
+fn load_settings() -> VaultResult<VaultSettingsRegistry> {
+    VaultSettingsRegistry::load().map_err(|e| VaultError::StorageError(e.to_string()))
+}
+
+fn save_settings(settings: &VaultSettingsRegistry) -> VaultResult<()> {
+    settings
+        .save()
+        .map_err(|e| VaultError::StorageError(e.to_string()))
+}
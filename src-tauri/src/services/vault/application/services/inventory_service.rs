@@ -0,0 +1,309 @@
+//! Inventory Service
+//!
+//! Exports a vault's file listing as CSV or JSON for people who need to know
+//! what a vault holds without receiving its contents. The listing comes from
+//! the manifest, so only the latest archive can be exported.
+//!
+//! Every export is recorded in the operation log, with the output path but
+//! no file names.
+
+use crate::prelude::*;
+use crate::services::file::infrastructure::file_operations::contains_traversal_attempt;
+use crate::services::shared::infrastructure::atomic_write_sync;
+use crate::services::vault::application::services::{
+    ArchiveService, OperationLogService, VaultService,
+};
+use crate::services::vault::domain::models::{
+    INVENTORY_SCHEMA, InventoryExportResult, InventoryFormat, InventorySource, VaultInventory,
+};
+use crate::services::vault::domain::{VaultError, VaultResult};
+use crate::services::vault::infrastructure::persistence::{
+    LoggedOperation, OperationLogEntry, VaultMetadata, configured_locale,
+};
+use chrono::{DateTime, Utc};
+use std::path::Path;
+
+/// Service exporting vault inventories
+#[derive(Debug)]
+pub struct InventoryService {
+    vault_service: VaultService,
+    archive_service: ArchiveService,
+}
+
+impl InventoryService {
+    pub fn new() -> Self {
+        Self {
+            vault_service: VaultService::new(),
+            archive_service: ArchiveService::new(),
+        }
+    }
+
+    /// Write the vault's inventory to `output_path`
+    ///
+    /// `archive_id` must be the latest archive when given. SHA-256 hashes are
+    /// only written when `include_hashes`.
+    pub async fn export(
+        &self,
+        vault_id: &str,
+        archive_id: Option<&str>,
+        format: InventoryFormat,
+        output_path: &Path,
+        include_hashes: bool,
+    ) -> VaultResult<InventoryExportResult> {
+        validate_output_path(output_path)?;
+
+        let metadata = self.vault_service.get_vault(vault_id).await?;
+        let archive_id = self
+            .archive_service
+            .resolve_latest_archive(&metadata, archive_id)?;
+        let inventory = build_inventory(&metadata, archive_id, include_hashes, Utc::now());
+
+        let content = match format {
+            InventoryFormat::Csv => inventory.to_csv(include_hashes, configured_locale()),
+            InventoryFormat::Json => inventory
+                .to_json()
+                .map_err(|e| VaultError::OperationFailed(e.to_string()))?,
+        };
+        atomic_write_sync(output_path, content.as_bytes())
+            .map_err(|e| VaultError::StorageError(e.to_string()))?;
+
+        let result = InventoryExportResult {
+            vault_id: vault_id.to_string(),
+            archive_id: inventory.archive_id.clone(),
+            output_path: output_path.display().to_string(),
+            format,
+            file_count: inventory.files.len(),
+            total_bytes: inventory.total_bytes(),
+            include_hashes,
+        };
+
+        let mut entry = OperationLogEntry::new(
+            LoggedOperation::InventoryExport,
+            vault_id,
+            format!(
+                "Exported {} files as {:?}{} to {}",
+                result.file_count,
+                format,
+                if include_hashes { " with hashes" } else { "" },
+                result.output_path
+            ),
+        );
+        entry.at = inventory.generated_at;
+        if let Err(e) = OperationLogService::new().record(entry) {
+            warn!(error = %e, "Failed to record inventory export in the operation log");
+        }
+
+        info!(
+            vault_id = %vault_id,
+            file_count = result.file_count,
+            format = ?format,
+            include_hashes,
+            "Exported vault inventory"
+        );
+        Ok(result)
+    }
+}
+
+impl Default for InventoryService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn build_inventory(
+    metadata: &VaultMetadata,
+    archive_id: Option<String>,
+    include_hashes: bool,
+    generated_at: DateTime<Utc>,
+) -> VaultInventory {
+    let sources = metadata.content.files.iter().map(|file| InventorySource {
+        path: &file.path,
+        size: file.size,
+        sha256: &file.sha256,
+    });
+
+    VaultInventory {
+        schema: INVENTORY_SCHEMA.to_string(),
+        vault_id: metadata.vault_id().to_string(),
+        vault_name: metadata.label().to_string(),
+        archive_id,
+        encryption_revision: metadata.encryption_revision(),
+        archived_at: metadata.last_encrypted_at(),
+        generated_at,
+        files: VaultInventory::files_from(sources, &metadata.items, include_hashes),
+    }
+}
+
+/// An absolute file path in an existing folder, free of traversal segments
+fn validate_output_path(path: &Path) -> VaultResult<()> {
+    let invalid = |reason: &str| {
+        VaultError::InvalidOperation(format!(
+            "Can't write the inventory to '{}': {}",
+            path.display(),
+            reason
+        ))
+    };
+
+    if !path.is_absolute() {
+        return Err(invalid("the path must be absolute"));
+    }
+    if contains_traversal_attempt(path) {
+        return Err(invalid("the path contains traversal segments"));
+    }
+    if path.is_dir() {
+        return Err(invalid("the path is a folder"));
+    }
+    if path.is_symlink() {
+        return Err(invalid("the path is a symbolic link"));
+    }
+    match path.parent() {
+        Some(parent) if parent.is_dir() => Ok(()),
+        _ => Err(invalid("the folder doesn't exist")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::file::infrastructure::file_operations::{HashAlgorithm, RawPath};
+    use crate::services::shared::infrastructure::{DeviceInfo, Locale, format_bytes};
+    use crate::services::vault::domain::models::UTF8_BOM;
+    use crate::services::vault::infrastructure::persistence::metadata::VaultFileEntry;
+    use rand::Rng;
+    use tempfile::TempDir;
+
+    /// Minimal RFC 4180 reader, to check the export parses back
+    fn parse_csv(text: &str) -> Vec<Vec<String>> {
+        let mut rows = Vec::new();
+        let mut row = Vec::new();
+        let mut field = String::new();
+        let mut quoted = false;
+        let mut chars = text.chars().peekable();
+        while let Some(c) = chars.next() {
+            match (quoted, c) {
+                (true, '"') if chars.peek() == Some(&'"') => {
+                    chars.next();
+                    field.push('"');
+                }
+                (true, '"') => quoted = false,
+                (true, c) => field.push(c),
+                (false, '"') => quoted = true,
+                (false, ',') => row.push(std::mem::take(&mut field)),
+                (false, '\r') if chars.peek() == Some(&'\n') => {
+                    chars.next();
+                    row.push(std::mem::take(&mut field));
+                    rows.push(std::mem::take(&mut row));
+                }
+                (false, c) => field.push(c),
+            }
+        }
+        rows
+    }
+
+    fn metadata_with(files: &[(&str, u64)]) -> VaultMetadata {
+        let device_info = DeviceInfo {
+            machine_id: "machine-1".to_string(),
+            machine_label: "laptop".to_string(),
+            created_at: Utc::now(),
+            app_version: "2.0.0".to_string(),
+        };
+        let files: Vec<VaultFileEntry> = files
+            .iter()
+            .map(|(path, size)| VaultFileEntry {
+                path: path.to_string(),
+                raw_path: RawPath::from_display(path),
+                lossy_name: false,
+                size: *size,
+                sha256: format!("{:064x}", size),
+                hash_algorithm: HashAlgorithm::Sha256,
+                digest: None,
+                ownership: None,
+                content_type: None,
+                content_type_mismatch: false,
+                transform: None,
+            })
+            .collect();
+        VaultMetadata::new(
+            "vault-001".to_string(),
+            "Estate".to_string(),
+            None,
+            "Estate".to_string(),
+            &device_info,
+            None,
+            vec![],
+            files.clone(),
+            files.len(),
+            files.iter().map(|f| f.size).sum(),
+        )
+    }
+
+    #[test]
+    fn test_csv_round_trips_awkward_file_names() {
+        let awkward = "letters/\"Dad's\" will, final\nsigned.pdf";
+        let metadata = metadata_with(&[(awkward, 1200), ("deed.pdf", 300), ("Caf\u{e9}.txt", 9)]);
+        let inventory = build_inventory(&metadata, None, true, Utc::now());
+
+        let csv = inventory.to_csv(true, Locale::En);
+        let body = csv.strip_prefix(UTF8_BOM).expect("CSV starts with a BOM");
+        let rows = parse_csv(body);
+
+        assert_eq!(rows[0], VaultInventory::csv_header(true));
+        let parsed: Vec<(String, u64, String)> = rows[1..]
+            .iter()
+            .map(|row| (row[0].clone(), row[1].parse().unwrap(), row[6].clone()))
+            .collect();
+        let mut expected: Vec<(String, u64, String)> = metadata
+            .content
+            .files
+            .iter()
+            .map(|f| (f.path.clone(), f.size, f.sha256.clone()))
+            .collect();
+        expected.sort();
+        assert_eq!(parsed, expected);
+    }
+
+    #[test]
+    fn test_csv_raw_sizes_survive_display_formatting() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..200 {
+            let size = rng.gen_range(0..u64::MAX) >> rng.gen_range(0..64);
+            let metadata = metadata_with(&[("file.bin", size)]);
+            let inventory = build_inventory(&metadata, None, false, Utc::now());
+
+            for locale in Locale::ALL {
+                let csv = inventory.to_csv(false, locale);
+                let rows = parse_csv(csv.strip_prefix(UTF8_BOM).unwrap());
+                assert_eq!(rows[1][1].parse::<u64>().unwrap(), size);
+                assert_eq!(rows[1][2], format_bytes(size, locale));
+            }
+        }
+    }
+
+    #[test]
+    fn test_json_round_trips_without_hashes() {
+        let metadata = metadata_with(&[("a, \"b\"\n.txt", 5)]);
+        let inventory =
+            build_inventory(&metadata, Some("archive-1".to_string()), false, Utc::now());
+
+        let parsed: VaultInventory = serde_json::from_str(&inventory.to_json().unwrap()).unwrap();
+        assert_eq!(parsed, inventory);
+        assert_eq!(parsed.files[0].path, "a, \"b\"\n.txt");
+        assert!(parsed.files[0].sha256.is_none());
+        assert_eq!(
+            inventory.to_csv(false, Locale::En).lines().next().unwrap(),
+            format!("{UTF8_BOM}path,size_bytes,size,archived_at,archived,items")
+        );
+    }
+
+    #[test]
+    fn test_output_path_validation() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir = temp_dir.path();
+
+        assert!(validate_output_path(&dir.join("inventory.csv")).is_ok());
+        assert!(validate_output_path(Path::new("relative.csv")).is_err());
+        assert!(validate_output_path(&dir.join("../escape.csv")).is_err());
+        assert!(validate_output_path(&dir.join("missing/inventory.csv")).is_err());
+        assert!(validate_output_path(dir).is_err());
+    }
+}
@@ -0,0 +1,386 @@
+//! Launch Watchdog Service
+//!
+//! Waits for the interface's `frontend_ready` handshake after startup and
+//! compares the bindings it was built with against the backend's. When the
+//! startup window closes, or the app exits, without a handshake, the launch
+//! is written to the launch diagnostics store with the page loads and window
+//! events seen meanwhile. A handshake arriving after that marks the record as
+//! a late start rather than a failed one.
+//!
+//! The watchdog keeps no clock of its own; callers pass the times, so the
+//! late and missing handshake paths are unit-testable.
+
+use crate::logging::VERSION;
+use crate::prelude::*;
+use crate::services::shared::infrastructure::atomic_write_sync;
+use crate::services::vault::domain::models::{
+    BindingsDrift, DEFAULT_FRONTEND_READY_TIMEOUT, FrontendHandshake, LAUNCH_DIAGNOSTICS_SCHEMA,
+    LaunchDiagnostics, LaunchOutcome, MAX_NAVIGATION_EVENTS, NavigationEvent,
+};
+use crate::services::vault::domain::{VaultError, VaultResult};
+use crate::services::vault::infrastructure::persistence::{AppConfig, LaunchDiagnosticsStore};
+use crate::types::DurationMs;
+use chrono::{DateTime, Utc};
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard, OnceLock};
+
+/// The launch being watched
+#[derive(Debug, Clone)]
+struct LaunchStart {
+    started_at: DateTime<Utc>,
+    timeout: DurationMs,
+    webview_version: Option<String>,
+}
+
+#[derive(Debug, Default)]
+struct WatchState {
+    start: Option<LaunchStart>,
+    handshake: Option<FrontendHandshake>,
+    /// Latest events first seen before the handshake, oldest first
+    navigation_events: VecDeque<NavigationEvent>,
+    /// A record of this launch has been written
+    recorded: bool,
+}
+
+/// Handshake state of this launch
+#[derive(Debug, Default)]
+pub struct LaunchWatchdog {
+    state: Mutex<WatchState>,
+    /// Store file; the config directory's when unset
+    store_path: Option<PathBuf>,
+}
+
+impl LaunchWatchdog {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Watchdog recording to `store_path` instead of the config directory
+    pub fn with_store_path(store_path: PathBuf) -> Self {
+        Self {
+            store_path: Some(store_path),
+            ..Self::default()
+        }
+    }
+
+    /// Watchdog of the running app
+    pub fn global() -> &'static Self {
+        static WATCHDOG: OnceLock<LaunchWatchdog> = OnceLock::new();
+        WATCHDOG.get_or_init(Self::new)
+    }
+
+    fn lock(&self) -> MutexGuard<'_, WatchState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Startup window set in the app config
+    pub fn configured_timeout() -> DurationMs {
+        match AppConfig::load() {
+            Ok(config) => config.frontend_ready_timeout,
+            Err(e) => {
+                warn!(error = %e, "Failed to load app config; using the default startup window");
+                DEFAULT_FRONTEND_READY_TIMEOUT
+            }
+        }
+    }
+
+    /// Start awaiting the handshake
+    pub fn start(
+        &self,
+        started_at: DateTime<Utc>,
+        timeout: DurationMs,
+        webview_version: Option<String>,
+    ) {
+        *self.lock() = WatchState {
+            start: Some(LaunchStart {
+                started_at,
+                timeout,
+                webview_version,
+            }),
+            ..WatchState::default()
+        };
+    }
+
+    /// Keep a page load or window event, while the handshake is awaited
+    pub fn record_navigation(&self, event: NavigationEvent) {
+        let mut state = self.lock();
+        if state.handshake.is_some() {
+            return;
+        }
+        if state.navigation_events.len() == MAX_NAVIGATION_EVENTS {
+            state.navigation_events.pop_front();
+        }
+        state.navigation_events.push_back(event);
+    }
+
+    /// Record the interface's handshake
+    ///
+    /// `expected_bindings_hash` is the hash of the bindings this backend
+    /// generates, when it could be rendered. A handshake after this launch
+    /// was recorded as failed turns the record into a late start.
+    pub fn record_handshake(
+        &self,
+        ui_version: &str,
+        bindings_hash: &str,
+        expected_bindings_hash: Option<&str>,
+        received_at: DateTime<Utc>,
+    ) -> VaultResult<FrontendHandshake> {
+        let mut state = self.lock();
+        let started_at = state
+            .start
+            .as_ref()
+            .map_or(received_at, |start| start.started_at);
+        let elapsed = (received_at - started_at).to_std().unwrap_or_default();
+
+        let handshake = FrontendHandshake {
+            ui_version: ui_version.to_string(),
+            bindings_hash: bindings_hash.to_string(),
+            expected_bindings_hash: expected_bindings_hash.map(str::to_string),
+            drift: BindingsDrift::compare(bindings_hash, expected_bindings_hash),
+            received_at,
+            elapsed: elapsed.into(),
+        };
+        let first = state.handshake.is_none();
+        state.handshake = Some(handshake.clone());
+
+        if first && state.recorded {
+            let mut store = self.load_store()?;
+            if let Some(record) = store
+                .failed_launch
+                .as_mut()
+                .filter(|record| record.started_at == started_at)
+            {
+                record.outcome = LaunchOutcome::LateHandshake;
+                record.handshake = Some(handshake.clone());
+                self.save_store(&store)?;
+                info!(
+                    elapsed_ms = handshake.elapsed.millis(),
+                    "Interface loaded late"
+                );
+            }
+        }
+        Ok(handshake)
+    }
+
+    /// The handshake of this launch, if one arrived
+    pub fn handshake(&self) -> Option<FrontendHandshake> {
+        self.lock().handshake.clone()
+    }
+
+    /// Record this launch as failed unless the handshake has arrived
+    ///
+    /// Called when the startup window closes and again on exit; only the
+    /// first call without a handshake writes a record.
+    pub fn expire(
+        &self,
+        now: DateTime<Utc>,
+        bindings_hash: Option<&str>,
+    ) -> VaultResult<Option<LaunchDiagnostics>> {
+        let mut state = self.lock();
+        let Some(start) = state.start.clone() else {
+            return Ok(None);
+        };
+        if state.handshake.is_some() || state.recorded {
+            return Ok(None);
+        }
+
+        let record = LaunchDiagnostics {
+            schema: LAUNCH_DIAGNOSTICS_SCHEMA.to_string(),
+            app_version: VERSION.to_string(),
+            os: std::env::consts::OS.to_string(),
+            arch: std::env::consts::ARCH.to_string(),
+            webview_version: start.webview_version,
+            bindings_hash: bindings_hash.map(str::to_string),
+            started_at: start.started_at,
+            timeout: start.timeout,
+            recorded_at: now,
+            outcome: LaunchOutcome::NoHandshake,
+            handshake: None,
+            navigation_events: state.navigation_events.iter().cloned().collect(),
+        };
+
+        let mut store = self.load_store()?;
+        store.failed_launch = Some(record.clone());
+        self.save_store(&store)?;
+        state.recorded = true;
+
+        warn!(
+            timeout_ms = start.timeout.millis(),
+            navigation_events = record.navigation_events.len(),
+            "Interface didn't load; recorded launch diagnostics"
+        );
+        Ok(Some(record))
+    }
+
+    /// Record of the latest failed launch, until dismissed
+    pub fn failed_launch(&self) -> VaultResult<Option<LaunchDiagnostics>> {
+        Ok(self.load_store()?.failed_launch)
+    }
+
+    /// Record of a failed launch before this one, until dismissed
+    pub fn previous_failed_launch(&self) -> VaultResult<Option<LaunchDiagnostics>> {
+        let current = self.lock().start.as_ref().map(|start| start.started_at);
+        Ok(self
+            .failed_launch()?
+            .filter(|record| Some(record.started_at) != current))
+    }
+
+    /// Write the latest failed launch's record into `destination_dir`
+    pub fn export_failed_launch(&self, destination_dir: &Path) -> VaultResult<PathBuf> {
+        let record = self.failed_launch()?.ok_or_else(|| {
+            VaultError::InvalidOperation("No failed launch has been recorded".to_string())
+        })?;
+        if !destination_dir.is_dir() {
+            return Err(VaultError::InvalidOperation(format!(
+                "Export folder doesn't exist: {}",
+                destination_dir.display()
+            )));
+        }
+
+        let path = destination_dir.join(format!(
+            "barqly-launch-diagnostics-{}.json",
+            record.started_at.format("%Y%m%d-%H%M%S")
+        ));
+        let json = serde_json::to_string_pretty(&record)
+            .map_err(|e| VaultError::StorageError(format!("Failed to serialize: {e}")))?;
+        atomic_write_sync(&path, json.as_bytes()).map_err(|e| {
+            VaultError::StorageError(format!("Failed to write launch diagnostics: {e}"))
+        })?;
+
+        info!(path = %path.display(), "Exported launch diagnostics");
+        Ok(path)
+    }
+
+    /// Forget the latest failed launch
+    pub fn clear_failed_launch(&self) -> VaultResult<()> {
+        let mut store = self.load_store()?;
+        if store.failed_launch.take().is_some() {
+            self.save_store(&store)?;
+            info!("Cleared launch diagnostics");
+        }
+        Ok(())
+    }
+
+    fn load_store(&self) -> VaultResult<LaunchDiagnosticsStore> {
+        match &self.store_path {
+            Some(path) => LaunchDiagnosticsStore::load_from(path),
+            None => LaunchDiagnosticsStore::load(),
+        }
+        .map_err(|e| VaultError::StorageError(format!("Failed to load launch diagnostics: {e}")))
+    }
+
+    fn save_store(&self, store: &LaunchDiagnosticsStore) -> VaultResult<()> {
+        match &self.store_path {
+            Some(path) => store.save_to(path),
+            None => store.save(),
+        }
+        .map_err(|e| VaultError::StorageError(format!("Failed to save launch diagnostics: {e}")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::vault::domain::models::NavigationEventKind;
+    use chrono::Duration;
+    use tempfile::TempDir;
+
+    const UI_HASH: &str = "ab12";
+
+    fn started(temp_dir: &TempDir) -> (LaunchWatchdog, DateTime<Utc>) {
+        let watchdog =
+            LaunchWatchdog::with_store_path(temp_dir.path().join("launch_diagnostics.json"));
+        let started_at = Utc::now();
+        watchdog.start(
+            started_at,
+            DurationMs::from_secs(30),
+            Some("WebKit 605.1.15".to_string()),
+        );
+        watchdog.record_navigation(NavigationEvent {
+            at: started_at,
+            window_label: "main".to_string(),
+            kind: NavigationEventKind::LoadStarted,
+            url: Some("tauri://localhost/".to_string()),
+        });
+        (watchdog, started_at)
+    }
+
+    #[test]
+    fn test_missing_handshake_is_recorded() {
+        let temp_dir = TempDir::new().unwrap();
+        let (watchdog, started_at) = started(&temp_dir);
+        let deadline = started_at + Duration::seconds(30);
+
+        let record = watchdog.expire(deadline, Some(UI_HASH)).unwrap().unwrap();
+        assert_eq!(record.outcome, LaunchOutcome::NoHandshake);
+        assert_eq!(record.webview_version.as_deref(), Some("WebKit 605.1.15"));
+        assert_eq!(record.navigation_events.len(), 1);
+        assert_eq!(record.os, std::env::consts::OS);
+
+        // Exit after the window closed doesn't record the launch twice
+        assert!(watchdog.expire(deadline, None).unwrap().is_none());
+
+        // The next start finds it until it is cleared
+        let next_start =
+            LaunchWatchdog::with_store_path(temp_dir.path().join("launch_diagnostics.json"));
+        assert_eq!(next_start.failed_launch().unwrap(), Some(record));
+        next_start.clear_failed_launch().unwrap();
+        assert!(next_start.failed_launch().unwrap().is_none());
+    }
+
+    #[test]
+    fn test_late_handshake_updates_the_record() {
+        let temp_dir = TempDir::new().unwrap();
+        let (watchdog, started_at) = started(&temp_dir);
+        watchdog
+            .expire(started_at + Duration::seconds(30), Some(UI_HASH))
+            .unwrap();
+
+        let handshake = watchdog
+            .record_handshake(
+                "0.9.0",
+                UI_HASH,
+                Some(UI_HASH),
+                started_at + Duration::seconds(45),
+            )
+            .unwrap();
+        assert_eq!(handshake.drift, BindingsDrift::InSync);
+        assert_eq!(handshake.elapsed, DurationMs::from_secs(45));
+
+        let record = watchdog.failed_launch().unwrap().unwrap();
+        assert_eq!(record.outcome, LaunchOutcome::LateHandshake);
+        assert_eq!(record.handshake, Some(handshake));
+
+        // Surfaced on the next start, not during the launch that recorded it
+        assert!(watchdog.previous_failed_launch().unwrap().is_none());
+        let next_start =
+            LaunchWatchdog::with_store_path(temp_dir.path().join("launch_diagnostics.json"));
+        next_start.start(Utc::now(), DurationMs::from_secs(30), None);
+        assert_eq!(next_start.previous_failed_launch().unwrap(), Some(record));
+    }
+
+    #[test]
+    fn test_handshake_in_time_records_nothing() {
+        let temp_dir = TempDir::new().unwrap();
+        let (watchdog, started_at) = started(&temp_dir);
+
+        let handshake = watchdog
+            .record_handshake(
+                "0.9.0",
+                "other",
+                Some(UI_HASH),
+                started_at + Duration::seconds(2),
+            )
+            .unwrap();
+        assert_eq!(handshake.drift, BindingsDrift::Drifted);
+
+        assert!(
+            watchdog
+                .expire(started_at + Duration::seconds(30), Some(UI_HASH))
+                .unwrap()
+                .is_none()
+        );
+        assert!(watchdog.failed_launch().unwrap().is_none());
+    }
+}
@@ -0,0 +1,651 @@
+//! Maintenance Service
+//!
+//! Runs bulk maintenance as a matrix of tasks × vaults. Cells are queued
+//! through a bounded worker pool (`MAINTENANCE_CONCURRENCY`), each on a
+//! blocking thread, and progress is published per completed cell. A cell that
+//! errors or panics is recorded as failed; the rest of the matrix still runs.
+//!
+//! The last report is persisted so it can be shown again later.
+
+use crate::logging::current_trace_id;
+use crate::logging::spawn_blocking;
+use crate::prelude::*;
+use crate::services::crypto::infrastructure::archive_reader;
+use crate::services::file::infrastructure::file_operations::{ParitySidecar, generate_parity_path};
+use crate::services::key_management::shared::application::services::registry_service::{
+    KeyRegistryService, MergeStrategy,
+};
+use crate::services::shared::infrastructure::io::atomic_write_sync;
+use crate::services::shared::infrastructure::progress::update_global_progress;
+use crate::services::shared::infrastructure::{get_vault_manifest_path, get_vaults_directory};
+use crate::services::vault::application::services::{VaultStatisticsService, VaultStatus};
+use crate::services::vault::domain::models::{
+    MaintenanceCell, MaintenanceFindings, MaintenanceReport, MaintenanceTask,
+};
+use crate::services::vault::domain::{VaultError, VaultResult};
+use crate::services::vault::infrastructure::persistence::{MaintenanceHistory, VaultMetadata};
+use crate::types::{DurationMs, IoPriority, ProgressDetails, ProgressUpdate};
+use chrono::Utc;
+use futures::StreamExt;
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+/// Cells run at the same time; bulk maintenance shouldn't starve the UI of I/O
+pub const MAINTENANCE_CONCURRENCY: usize = 2;
+
+/// A vault resolved to the files maintenance works on
+#[derive(Debug, Clone)]
+pub struct MaintenanceTarget {
+    pub vault_id: String,
+    pub vault_name: String,
+    pub sanitized_name: String,
+    pub archive_path: PathBuf,
+    pub manifest_path: PathBuf,
+}
+
+impl MaintenanceTarget {
+    /// Resolve a vault's archive and manifest in the standard locations
+    pub fn for_vault(vault: &VaultMetadata) -> VaultResult<Self> {
+        let sanitized_name = vault.vault.sanitized_name.clone();
+        let vaults_dir =
+            get_vaults_directory().map_err(|e| VaultError::StorageError(e.to_string()))?;
+        let manifest_path = get_vault_manifest_path(&sanitized_name)
+            .map_err(|e| VaultError::StorageError(e.to_string()))?;
+
+        Ok(Self {
+            vault_id: vault.vault_id().to_string(),
+            vault_name: vault.label().to_string(),
+            archive_path: vaults_dir.join(format!("{}.age", sanitized_name)),
+            manifest_path,
+            sanitized_name,
+        })
+    }
+}
+
+/// Runs a single maintenance task against a single vault
+pub trait MaintenanceExecutor: Send + Sync {
+    fn execute(
+        &self,
+        target: &MaintenanceTarget,
+        task: MaintenanceTask,
+    ) -> Result<MaintenanceFindings, String>;
+}
+
+/// Executor backed by the real statistics, manifest, and registry services
+#[derive(Debug, Default)]
+pub struct VaultMaintenanceExecutor {
+    /// Registry merges are load-modify-save; serialize them across cells
+    registry_lock: Mutex<()>,
+}
+
+impl MaintenanceExecutor for VaultMaintenanceExecutor {
+    fn execute(
+        &self,
+        target: &MaintenanceTarget,
+        task: MaintenanceTask,
+    ) -> Result<MaintenanceFindings, String> {
+        match task {
+            MaintenanceTask::StatisticsRefresh => refresh_statistics(target),
+            MaintenanceTask::IntegrityVerification => {
+                verify_archive_integrity(&target.archive_path, &target.manifest_path)
+            }
+            MaintenanceTask::ManifestMigration => migrate_manifest(&target.manifest_path),
+            MaintenanceTask::RegistryReconciliation => {
+                let _guard = self.registry_lock.lock().map_err(|e| e.to_string())?;
+                reconcile_registry(target)
+            }
+        }
+    }
+}
+
+/// Service for bulk vault maintenance
+pub struct MaintenanceService {
+    executor: Arc<dyn MaintenanceExecutor>,
+}
+
+impl MaintenanceService {
+    pub fn new() -> Self {
+        Self::with_executor(Arc::new(VaultMaintenanceExecutor::default()))
+    }
+
+    pub fn with_executor(executor: Arc<dyn MaintenanceExecutor>) -> Self {
+        Self { executor }
+    }
+
+    /// Run every task against every target and save the combined report
+    pub async fn run_maintenance(
+        &self,
+        operation_id: &str,
+        targets: Vec<MaintenanceTarget>,
+        tasks: Vec<MaintenanceTask>,
+    ) -> VaultResult<MaintenanceReport> {
+        let report = self.run_matrix(operation_id, targets, tasks).await;
+
+        let history = MaintenanceHistory {
+            last_report: Some(report.clone()),
+            ..Default::default()
+        };
+        history
+            .save()
+            .map_err(|e| VaultError::StorageError(e.to_string()))?;
+
+        Ok(report)
+    }
+
+    /// Report from the most recent run on this device
+    pub fn get_last_report(&self) -> VaultResult<Option<MaintenanceReport>> {
+        MaintenanceHistory::load()
+            .map(|history| history.last_report)
+            .map_err(|e| VaultError::StorageError(e.to_string()))
+    }
+
+    /// Execute the matrix without persisting the report
+    pub async fn run_matrix(
+        &self,
+        operation_id: &str,
+        targets: Vec<MaintenanceTarget>,
+        tasks: Vec<MaintenanceTask>,
+    ) -> MaintenanceReport {
+        let started_at = Utc::now();
+        let vault_ids: Vec<String> = targets.iter().map(|t| t.vault_id.clone()).collect();
+        let total_cells = targets.len() * tasks.len();
+        let completed = Arc::new(AtomicUsize::new(0));
+
+        info!(
+            operation_id,
+            vault_count = targets.len(),
+            task_count = tasks.len(),
+            "Starting vault maintenance"
+        );
+
+        let queue: Vec<(Arc<MaintenanceTarget>, MaintenanceTask)> = targets
+            .into_iter()
+            .map(Arc::new)
+            .flat_map(|target| tasks.iter().map(move |task| (Arc::clone(&target), *task)))
+            .collect();
+
+        let cells: Vec<MaintenanceCell> = futures::stream::iter(queue)
+            .map(|(target, task)| {
+                let executor = Arc::clone(&self.executor);
+                let completed = Arc::clone(&completed);
+                async move {
+                    let cell = run_cell(executor, &target, task).await;
+                    let done = completed.fetch_add(1, Ordering::SeqCst) + 1;
+                    report_progress(operation_id, &cell, done, total_cells);
+                    cell
+                }
+            })
+            .buffer_unordered(MAINTENANCE_CONCURRENCY)
+            .collect()
+            .await;
+
+        let report = MaintenanceReport::new(operation_id, started_at, tasks, vault_ids, cells);
+        info!(
+            operation_id,
+            passed = report.passed,
+            issues_found = report.issues_found,
+            failed = report.failed,
+            "Vault maintenance finished"
+        );
+        report
+    }
+}
+
+impl Default for MaintenanceService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+async fn run_cell(
+    executor: Arc<dyn MaintenanceExecutor>,
+    target: &Arc<MaintenanceTarget>,
+    task: MaintenanceTask,
+) -> MaintenanceCell {
+    let start = Instant::now();
+    let result = spawn_blocking({
+        let target = Arc::clone(target);
+        move || executor.execute(&target, task)
+    })
+    .await
+    .unwrap_or_else(|e| Err(format!("Task aborted: {}", e)));
+    let duration_ms = DurationMs::from(start.elapsed());
+
+    if let Err(e) = &result {
+        warn!(vault_id = %target.vault_id, task = ?task, error = %e, "Maintenance task failed");
+    }
+
+    MaintenanceCell::from_result(
+        &target.vault_id,
+        &target.vault_name,
+        task,
+        duration_ms,
+        result,
+    )
+}
+
+fn report_progress(operation_id: &str, cell: &MaintenanceCell, done: usize, total: usize) {
+    update_global_progress(
+        operation_id,
+        ProgressUpdate {
+            operation_id: operation_id.to_string(),
+            progress: done as f32 / total.max(1) as f32,
+            message: format!("{}: {}", cell.task.label(), cell.vault_name),
+            details: Some(ProgressDetails::Maintenance {
+                vault_id: cell.vault_id.clone(),
+                task: cell.task.label().to_string(),
+                cells_completed: done,
+                total_cells: total,
+            }),
+            timestamp: Utc::now(),
+            estimated_time_remaining: None,
+            io_priority: IoPriority::Normal,
+            trace_id: current_trace_id(),
+        },
+    );
+}
+
+/// Recompute statistics and flag vaults whose files disagree
+fn refresh_statistics(target: &MaintenanceTarget) -> Result<MaintenanceFindings, String> {
+    let statistics = VaultStatisticsService::new()
+        .get_vault_statistics(&target.sanitized_name)
+        .map_err(|e| e.to_string())?;
+
+    let mut findings = MaintenanceFindings::default();
+    match statistics.status {
+        VaultStatus::Orphaned => findings
+            .issues
+            .push("Archive exists but the manifest is missing or unreadable".to_string()),
+        VaultStatus::Incomplete => findings
+            .issues
+            .push("Manifest exists but the archive is missing".to_string()),
+        VaultStatus::New | VaultStatus::Active => {}
+    }
+    findings.actions.push(format!(
+        "{} encryptions, {} files",
+        statistics.encryption_count, statistics.file_count
+    ));
+    Ok(findings)
+}
+
+fn load_manifest(path: &Path) -> Result<VaultMetadata, String> {
+    let content = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let manifest: VaultMetadata = serde_json::from_str(&content).map_err(|e| e.to_string())?;
+    manifest.validate().map_err(|e| e.to_string())?;
+    Ok(manifest)
+}
+
+/// Check that the archive exists when it should and has a readable age header
+///
+/// Without a key only the header can be checked; payload corruption is
+/// detected at decryption time, unless the archive has a parity sidecar, in
+/// which case every block is checked against the sidecar's hashes.
+pub fn verify_archive_integrity(
+    archive_path: &Path,
+    manifest_path: &Path,
+) -> Result<MaintenanceFindings, String> {
+    let mut findings = MaintenanceFindings::default();
+    let revision = load_manifest(manifest_path)
+        .ok()
+        .map(|m| m.encryption_revision());
+
+    if !archive_path.exists() {
+        if revision.is_some_and(|r| r > 0) {
+            findings
+                .issues
+                .push("Archive is missing for an encrypted vault".to_string());
+        }
+        return Ok(findings);
+    }
+
+    let file = std::fs::File::open(archive_path).map_err(|e| e.to_string())?;
+    if file.metadata().map_err(|e| e.to_string())?.len() == 0 {
+        findings.issues.push("Archive is empty".to_string());
+        return Ok(findings);
+    }
+    let header = archive_reader(BufReader::new(file))
+        .map_err(|e| e.to_string())
+        .and_then(|reader| age::Decryptor::new(reader).map_err(|e| e.to_string()));
+    if let Err(e) = header {
+        findings
+            .issues
+            .push(format!("Archive header is unreadable: {}", e));
+    }
+    if revision.is_none() {
+        findings
+            .issues
+            .push("Archive has no readable manifest".to_string());
+    }
+    check_parity(archive_path, &mut findings);
+    Ok(findings)
+}
+
+/// Compare the archive against its parity sidecar, if it has one
+fn check_parity(archive_path: &Path, findings: &mut MaintenanceFindings) {
+    let sidecar_path = generate_parity_path(archive_path);
+    let sidecar = match ParitySidecar::open(&sidecar_path) {
+        Ok(Some(sidecar)) => sidecar,
+        Ok(None) => return,
+        Err(e) => {
+            findings
+                .issues
+                .push(format!("Parity file is unreadable: {}", e));
+            return;
+        }
+    };
+    let check = match sidecar.check(archive_path) {
+        Ok(check) => check,
+        Err(e) => {
+            findings.issues.push(format!("Parity check failed: {}", e));
+            return;
+        }
+    };
+
+    if check.is_intact() {
+        findings
+            .actions
+            .push("Archive matches its parity".to_string());
+    } else if check.is_repairable() {
+        findings.issues.push(format!(
+            "{} damaged bytes and {} damaged parity blocks; repairable from parity",
+            check.damaged_bytes(),
+            check.damaged_parity_blocks
+        ));
+    } else {
+        findings.issues.push(format!(
+            "{} damaged bytes, {} ranges beyond what the parity can repair",
+            check.damaged_bytes(),
+            check.unrepairable_ranges.len()
+        ));
+    }
+}
+
+/// Rewrite the manifest in the current schema if it lacks newer fields
+pub fn migrate_manifest(manifest_path: &Path) -> Result<MaintenanceFindings, String> {
+    let mut findings = MaintenanceFindings::default();
+    if !manifest_path.exists() {
+        findings.issues.push("Manifest is missing".to_string());
+        return Ok(findings);
+    }
+
+    let content = std::fs::read_to_string(manifest_path).map_err(|e| e.to_string())?;
+    let on_disk: serde_json::Value = match serde_json::from_str(&content) {
+        Ok(value) => value,
+        Err(e) => {
+            findings
+                .issues
+                .push(format!("Manifest is not valid JSON: {}", e));
+            return Ok(findings);
+        }
+    };
+    let manifest: VaultMetadata = match serde_json::from_value(on_disk.clone()) {
+        Ok(manifest) => manifest,
+        Err(e) => {
+            findings
+                .issues
+                .push(format!("Manifest doesn't match a known schema: {}", e));
+            return Ok(findings);
+        }
+    };
+    if let Err(e) = manifest.validate() {
+        findings.issues.push(format!("Manifest is invalid: {}", e));
+        return Ok(findings);
+    }
+
+    let current = serde_json::to_value(&manifest).map_err(|e| e.to_string())?;
+    if current != on_disk {
+        let json = serde_json::to_string_pretty(&manifest).map_err(|e| e.to_string())?;
+        atomic_write_sync(manifest_path, json.as_bytes()).map_err(|e| e.to_string())?;
+        findings
+            .actions
+            .push(format!("Rewrote manifest as {}", manifest.schema));
+    }
+    Ok(findings)
+}
+
+/// Merge the manifest's recipients into the key registry
+fn reconcile_registry(target: &MaintenanceTarget) -> Result<MaintenanceFindings, String> {
+    let manifest = load_manifest(&target.manifest_path)?;
+    let keys_added = KeyRegistryService::new()
+        .merge_keys_from_manifest(&manifest, &target.vault_id, MergeStrategy::Additive)
+        .map_err(|e| e.to_string())?;
+
+    let mut findings = MaintenanceFindings::default();
+    if keys_added > 0 {
+        findings
+            .actions
+            .push(format!("Added {} keys to the registry", keys_added));
+    }
+    Ok(findings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::file::infrastructure::file_operations::generate_parity;
+    use crate::services::shared::infrastructure::DeviceInfo;
+    use crate::services::shared::infrastructure::progress::get_global_progress;
+    use crate::services::vault::domain::models::MaintenanceCellStatus;
+    use tempfile::TempDir;
+
+    /// Real integrity and migration checks; other tasks are stubbed, and one
+    /// vault's statistics task errors to prove failures stay in their cell
+    struct FixtureExecutor {
+        inner: VaultMaintenanceExecutor,
+        failing_vault: &'static str,
+    }
+
+    impl MaintenanceExecutor for FixtureExecutor {
+        fn execute(
+            &self,
+            target: &MaintenanceTarget,
+            task: MaintenanceTask,
+        ) -> Result<MaintenanceFindings, String> {
+            match task {
+                MaintenanceTask::IntegrityVerification | MaintenanceTask::ManifestMigration => {
+                    self.inner.execute(target, task)
+                }
+                MaintenanceTask::StatisticsRefresh if target.vault_id == self.failing_vault => {
+                    Err("statistics unavailable".to_string())
+                }
+                _ => Ok(MaintenanceFindings::default()),
+            }
+        }
+    }
+
+    fn manifest(vault_id: &str, name: &str, revision: u32) -> VaultMetadata {
+        let device_info = DeviceInfo {
+            machine_id: "machine-1".to_string(),
+            machine_label: "laptop".to_string(),
+            created_at: Utc::now(),
+            app_version: "2.0.0".to_string(),
+        };
+        let mut manifest = VaultMetadata::new(
+            vault_id.to_string(),
+            name.to_string(),
+            None,
+            name.to_string(),
+            &device_info,
+            None,
+            vec![],
+            vec![],
+            0,
+            0,
+        );
+        for _ in 0..revision {
+            manifest.increment_version(&device_info);
+        }
+        manifest
+    }
+
+    /// Write a fixture vault: manifest plus an archive with the given bytes
+    fn fixture_vault(
+        dir: &Path,
+        vault_id: &str,
+        name: &str,
+        archive: Option<&[u8]>,
+    ) -> MaintenanceTarget {
+        let manifest_path = dir.join(format!("{}.manifest", name));
+        let archive_path = dir.join(format!("{}.age", name));
+        let revision = u32::from(archive.is_some());
+        let json = serde_json::to_string_pretty(&manifest(vault_id, name, revision)).unwrap();
+        std::fs::write(&manifest_path, json).unwrap();
+        if let Some(bytes) = archive {
+            std::fs::write(&archive_path, bytes).unwrap();
+        }
+
+        MaintenanceTarget {
+            vault_id: vault_id.to_string(),
+            vault_name: name.to_string(),
+            sanitized_name: name.to_string(),
+            archive_path,
+            manifest_path,
+        }
+    }
+
+    fn valid_archive() -> Vec<u8> {
+        let recipient = age::x25519::Identity::generate().to_public();
+        age::encrypt(&recipient, b"fixture payload").unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_matrix_isolates_failures() {
+        let temp_dir = TempDir::new().unwrap();
+        let healthy = fixture_vault(
+            temp_dir.path(),
+            "v-healthy",
+            "Healthy",
+            Some(&valid_archive()),
+        );
+        let corrupted = fixture_vault(
+            temp_dir.path(),
+            "v-corrupt",
+            "Corrupted",
+            Some(b"this is not an age archive"),
+        );
+        let fresh = fixture_vault(temp_dir.path(), "v-new", "Fresh", None);
+
+        let service = MaintenanceService::with_executor(Arc::new(FixtureExecutor {
+            inner: VaultMaintenanceExecutor::default(),
+            failing_vault: "v-healthy",
+        }));
+        let tasks = vec![
+            MaintenanceTask::IntegrityVerification,
+            MaintenanceTask::StatisticsRefresh,
+            MaintenanceTask::ManifestMigration,
+        ];
+        let report = service
+            .run_matrix(
+                "maintenance_test_isolation",
+                vec![healthy, corrupted, fresh],
+                tasks.clone(),
+            )
+            .await;
+
+        assert_eq!(report.tasks, tasks);
+        assert_eq!(report.vault_ids, vec!["v-healthy", "v-corrupt", "v-new"]);
+        assert_eq!(report.cells.len(), 9);
+        assert_eq!(
+            (report.passed, report.issues_found, report.failed),
+            (7, 1, 1)
+        );
+
+        let corrupt_integrity = report
+            .cell("v-corrupt", MaintenanceTask::IntegrityVerification)
+            .unwrap();
+        assert_eq!(corrupt_integrity.status, MaintenanceCellStatus::IssuesFound);
+        assert!(corrupt_integrity.issues[0].contains("header"));
+
+        let failed_stats = report
+            .cell("v-healthy", MaintenanceTask::StatisticsRefresh)
+            .unwrap();
+        assert_eq!(failed_stats.status, MaintenanceCellStatus::Failed);
+        assert_eq!(
+            failed_stats.error.as_deref(),
+            Some("statistics unavailable")
+        );
+
+        // The failure didn't stop the rest of the healthy vault's row
+        for task in [
+            MaintenanceTask::IntegrityVerification,
+            MaintenanceTask::ManifestMigration,
+        ] {
+            let cell = report.cell("v-healthy", task).unwrap();
+            assert_eq!(cell.status, MaintenanceCellStatus::Passed, "{:?}", task);
+        }
+
+        let progress = get_global_progress("maintenance_test_isolation").unwrap();
+        assert_eq!(progress.progress, 1.0);
+        assert!(matches!(
+            progress.details,
+            Some(ProgressDetails::Maintenance {
+                cells_completed: 9,
+                total_cells: 9,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_integrity_flags_missing_archive() {
+        let temp_dir = TempDir::new().unwrap();
+        let target = fixture_vault(temp_dir.path(), "v-1", "Vault", Some(&valid_archive()));
+        std::fs::remove_file(&target.archive_path).unwrap();
+
+        let findings =
+            verify_archive_integrity(&target.archive_path, &target.manifest_path).unwrap();
+        assert_eq!(findings.issues.len(), 1);
+        assert!(findings.issues[0].contains("missing"));
+    }
+
+    #[test]
+    fn test_integrity_uses_parity_when_present() {
+        let temp_dir = TempDir::new().unwrap();
+        let target = fixture_vault(temp_dir.path(), "v-1", "Vault", Some(&valid_archive()));
+        generate_parity(&target.archive_path, 50, &|_, _| {}).unwrap();
+
+        let clean = verify_archive_integrity(&target.archive_path, &target.manifest_path).unwrap();
+        assert!(clean.issues.is_empty(), "{:?}", clean.issues);
+        assert!(clean.actions[0].contains("parity"));
+
+        let mut archive = std::fs::read(&target.archive_path).unwrap();
+        let last = archive.len() - 1;
+        archive[last] ^= 0xff;
+        std::fs::write(&target.archive_path, archive).unwrap();
+
+        let damaged =
+            verify_archive_integrity(&target.archive_path, &target.manifest_path).unwrap();
+        assert_eq!(damaged.issues.len(), 1);
+        assert!(
+            damaged.issues[0].contains("repairable"),
+            "{:?}",
+            damaged.issues
+        );
+    }
+
+    #[test]
+    fn test_migrate_manifest_fills_new_fields() {
+        let temp_dir = TempDir::new().unwrap();
+        let target = fixture_vault(temp_dir.path(), "v-1", "Vault", None);
+
+        // Drop a defaulted field to simulate a manifest written by an older release
+        let mut value: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&target.manifest_path).unwrap()).unwrap();
+        value.as_object_mut().unwrap().remove("bundle_type");
+        std::fs::write(&target.manifest_path, value.to_string()).unwrap();
+
+        let findings = migrate_manifest(&target.manifest_path).unwrap();
+        assert!(findings.issues.is_empty());
+        assert_eq!(findings.actions.len(), 1);
+
+        let again = migrate_manifest(&target.manifest_path).unwrap();
+        assert!(again.actions.is_empty());
+
+        std::fs::write(&target.manifest_path, "{ not json").unwrap();
+        let broken = migrate_manifest(&target.manifest_path).unwrap();
+        assert_eq!(broken.issues.len(), 1);
+    }
+}
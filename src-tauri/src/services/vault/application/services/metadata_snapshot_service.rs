@@ -0,0 +1,662 @@
+//! Metadata Snapshot Service
+//!
+//! Lists the restore points taken before mutating operations, describes what
+//! changed since one, and restores it. A restore needs the typed confirmation,
+//! first takes a restore point of the state it replaces, and writes every file
+//! as one journaled mutation.
+//!
+//! Restoring only writes files the snapshot holds: a vault created after the
+//! snapshot keeps its manifest.
+
+use crate::prelude::*;
+use crate::services::key_management::shared::KeyRegistry;
+use crate::services::shared::infrastructure::{MutationJournal, PendingWrite};
+use crate::services::vault::domain::models::{
+    ArchiveIndexEntry, MetadataChange, MetadataChangeKind, MetadataRestoreResult,
+    MetadataSnapshotDiff, MetadataSnapshotSummary, RESTORE_SNAPSHOT_CONFIRMATION,
+};
+use crate::services::vault::domain::{VaultError, VaultResult};
+use crate::services::vault::infrastructure::persistence::{
+    ArchiveIndex, MetadataSnapshotStore, SnapshotFileKind, SnapshotLayout, SnapshotRecord,
+    VaultMetadata,
+};
+use chrono::Utc;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fs;
+
+/// Service for browsing and restoring metadata snapshots
+#[derive(Debug, Default)]
+pub struct MetadataSnapshotService;
+
+impl MetadataSnapshotService {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Restore points, newest first
+    pub fn list(&self) -> VaultResult<Vec<MetadataSnapshotSummary>> {
+        list_in(&open_store()?)
+    }
+
+    /// What changed between a restore point and now
+    pub fn diff(&self, snapshot_id: &str) -> VaultResult<MetadataSnapshotDiff> {
+        diff_in(&open_store()?, &current_layout()?, snapshot_id)
+    }
+
+    /// Put the registry, manifests and archive index back as they were
+    pub fn restore(
+        &self,
+        snapshot_id: &str,
+        confirmation: Option<&str>,
+    ) -> VaultResult<MetadataRestoreResult> {
+        let journal =
+            MutationJournal::open().map_err(|e| VaultError::StorageError(e.to_string()))?;
+        restore_in(
+            &open_store()?,
+            &journal,
+            &current_layout()?,
+            snapshot_id,
+            confirmation,
+        )
+    }
+}
+
+fn open_store() -> VaultResult<MetadataSnapshotStore> {
+    MetadataSnapshotStore::open().map_err(|e| VaultError::StorageError(e.to_string()))
+}
+
+fn current_layout() -> VaultResult<SnapshotLayout> {
+    SnapshotLayout::current().map_err(|e| VaultError::StorageError(e.to_string()))
+}
+
+fn list_in(store: &MetadataSnapshotStore) -> VaultResult<Vec<MetadataSnapshotSummary>> {
+    let snapshots = store
+        .list()
+        .map_err(|e| VaultError::StorageError(e.to_string()))?;
+    Ok(snapshots.iter().map(summarize).collect())
+}
+
+fn diff_in(
+    store: &MetadataSnapshotStore,
+    layout: &SnapshotLayout,
+    snapshot_id: &str,
+) -> VaultResult<MetadataSnapshotDiff> {
+    let record = find_snapshot(store, snapshot_id)?;
+    let before = MetadataState::from_snapshot(store, &record)?;
+    let after = MetadataState::from_disk(layout)?;
+
+    Ok(MetadataSnapshotDiff {
+        snapshot: summarize(&record),
+        changes: diff_states(&before, &after),
+    })
+}
+
+fn restore_in(
+    store: &MetadataSnapshotStore,
+    journal: &MutationJournal,
+    layout: &SnapshotLayout,
+    snapshot_id: &str,
+    confirmation: Option<&str>,
+) -> VaultResult<MetadataRestoreResult> {
+    if confirmation != Some(RESTORE_SNAPSHOT_CONFIRMATION) {
+        return Err(VaultError::InvalidOperation(format!(
+            "Type '{}' to confirm restoring this snapshot",
+            RESTORE_SNAPSHOT_CONFIRMATION
+        )));
+    }
+
+    let record = find_snapshot(store, snapshot_id)?;
+    if let Some(file) = record.files.iter().find(|file| !layout.allows(file)) {
+        return Err(VaultError::InvalidOperation(format!(
+            "Snapshot '{}' points outside the metadata folders ('{}')",
+            snapshot_id,
+            file.path.display()
+        )));
+    }
+
+    let before = MetadataState::from_snapshot(store, &record)?;
+    let reverted = diff_states(&before, &MetadataState::from_disk(layout)?);
+
+    let writes = record
+        .files
+        .iter()
+        .map(|file| {
+            store
+                .read_file(file)
+                .map(|contents| PendingWrite::new(&file.path, contents))
+        })
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| VaultError::StorageError(e.to_string()))?;
+
+    // A restore point of what's being replaced, so the restore can be undone
+    let undo_snapshot_id = match layout
+        .sources()
+        .and_then(|sources| store.capture("restore_metadata_snapshot", &sources, Utc::now()))
+    {
+        Ok(undo) => Some(undo.id),
+        Err(e) => {
+            warn!(error = %e, "Failed to capture a snapshot before restoring");
+            None
+        }
+    };
+
+    let plan = journal
+        .apply("restore_metadata_snapshot", writes)
+        .map_err(|e| VaultError::StorageError(e.to_string()))?;
+
+    info!(
+        snapshot_id = %snapshot_id,
+        restored_files = plan.changed_files().count(),
+        reverted = reverted.len(),
+        "Restored metadata snapshot"
+    );
+    Ok(MetadataRestoreResult {
+        snapshot_id: snapshot_id.to_string(),
+        restored_files: plan.changed_files().count(),
+        undo_snapshot_id,
+        reverted,
+    })
+}
+
+fn find_snapshot(store: &MetadataSnapshotStore, snapshot_id: &str) -> VaultResult<SnapshotRecord> {
+    store
+        .get(snapshot_id)
+        .map_err(|e| VaultError::StorageError(e.to_string()))?
+        .ok_or_else(|| VaultError::NotFound(format!("snapshot {}", snapshot_id)))
+}
+
+fn summarize(record: &SnapshotRecord) -> MetadataSnapshotSummary {
+    let has = |kind| record.files.iter().any(|file| file.kind == kind);
+    MetadataSnapshotSummary {
+        snapshot_id: record.id.clone(),
+        created_at: record.created_at,
+        operation: record.operation.clone(),
+        manifest_count: record
+            .files
+            .iter()
+            .filter(|file| file.kind == SnapshotFileKind::Manifest)
+            .count(),
+        includes_registry: has(SnapshotFileKind::Registry),
+        includes_archive_index: has(SnapshotFileKind::ArchiveIndex),
+    }
+}
+
+/// Parsed metadata at one point in time
+#[derive(Debug, Default)]
+struct MetadataState {
+    registry: Option<KeyRegistry>,
+    archive_index: Option<ArchiveIndex>,
+    manifests: Vec<VaultMetadata>,
+}
+
+impl MetadataState {
+    fn from_snapshot(store: &MetadataSnapshotStore, record: &SnapshotRecord) -> VaultResult<Self> {
+        let mut state = Self::default();
+        for file in &record.files {
+            let contents = store
+                .read_file(file)
+                .map_err(|e| VaultError::StorageError(e.to_string()))?;
+            state.add(file.kind, &contents);
+        }
+        Ok(state)
+    }
+
+    fn from_disk(layout: &SnapshotLayout) -> VaultResult<Self> {
+        let sources = layout
+            .sources()
+            .map_err(|e| VaultError::StorageError(e.to_string()))?;
+        let mut state = Self::default();
+        for source in sources {
+            if let Ok(contents) = fs::read(&source.path) {
+                state.add(source.kind, &contents);
+            }
+        }
+        Ok(state)
+    }
+
+    /// Files that don't parse are left out of the comparison
+    fn add(&mut self, kind: SnapshotFileKind, contents: &[u8]) {
+        let parsed = match kind {
+            SnapshotFileKind::Registry => {
+                serde_json::from_slice(contents).map(|registry| self.registry = Some(registry))
+            }
+            SnapshotFileKind::ArchiveIndex => {
+                serde_json::from_slice(contents).map(|index| self.archive_index = Some(index))
+            }
+            SnapshotFileKind::Manifest => {
+                serde_json::from_slice(contents).map(|manifest| self.manifests.push(manifest))
+            }
+        };
+        if let Err(e) = parsed {
+            warn!(kind = ?kind, error = %e, "Skipping unreadable metadata file in comparison");
+        }
+    }
+
+    fn vault_label<'a>(&'a self, vault_id: &'a str) -> &'a str {
+        self.manifests
+            .iter()
+            .find(|manifest| manifest.vault_id() == vault_id)
+            .map(|manifest| manifest.label())
+            .unwrap_or(vault_id)
+    }
+}
+
+/// Changes that turn `before` into `after`, sorted by kind
+fn diff_states(before: &MetadataState, after: &MetadataState) -> Vec<MetadataChange> {
+    let mut changes = Vec::new();
+
+    // Keys, by ID
+    let labels = |state: &MetadataState| -> BTreeMap<String, String> {
+        state
+            .registry
+            .iter()
+            .flat_map(|registry| registry.keys.iter())
+            .map(|(id, entry)| (id.clone(), entry.label().to_string()))
+            .collect()
+    };
+    let (keys_before, keys_after) = (labels(before), labels(after));
+    for (id, label) in &keys_after {
+        match keys_before.get(id) {
+            None => changes.push(MetadataChange::new(
+                MetadataChangeKind::KeyAdded,
+                format!("Key '{}' was added", label),
+            )),
+            Some(old) if old != label => changes.push(MetadataChange::new(
+                MetadataChangeKind::KeyRenamed,
+                format!("Key '{}' was renamed to '{}'", old, label),
+            )),
+            Some(_) => {}
+        }
+    }
+    for (id, label) in &keys_before {
+        if !keys_after.contains_key(id) {
+            changes.push(MetadataChange::new(
+                MetadataChangeKind::KeyRemoved,
+                format!("Key '{}' was removed", label),
+            ));
+        }
+    }
+
+    // Vaults and their attached keys, by vault ID
+    let vaults = |state: &'_ MetadataState| -> BTreeMap<String, usize> {
+        state
+            .manifests
+            .iter()
+            .enumerate()
+            .map(|(i, manifest)| (manifest.vault_id().to_string(), i))
+            .collect()
+    };
+    let (vaults_before, vaults_after) = (vaults(before), vaults(after));
+    for (id, &i) in &vaults_after {
+        let manifest = &after.manifests[i];
+        let Some(&j) = vaults_before.get(id) else {
+            changes.push(MetadataChange::new(
+                MetadataChangeKind::VaultAdded,
+                format!("Vault '{}' was created", manifest.label()),
+            ));
+            continue;
+        };
+        let old = &before.manifests[j];
+        if old.label() != manifest.label() {
+            changes.push(MetadataChange::new(
+                MetadataChangeKind::VaultRenamed,
+                format!(
+                    "Vault '{}' was renamed to '{}'",
+                    old.label(),
+                    manifest.label()
+                ),
+            ));
+        }
+
+        let recipients = |m: &VaultMetadata| -> BTreeMap<String, String> {
+            m.recipients()
+                .iter()
+                .map(|r| (r.key_id.clone(), r.label.clone()))
+                .collect()
+        };
+        let (old_keys, new_keys) = (recipients(old), recipients(manifest));
+        for (key_id, label) in &new_keys {
+            if !old_keys.contains_key(key_id) {
+                changes.push(MetadataChange::new(
+                    MetadataChangeKind::KeyAttached,
+                    format!(
+                        "Key '{}' was attached to vault '{}'",
+                        label,
+                        manifest.label()
+                    ),
+                ));
+            }
+        }
+        for (key_id, label) in &old_keys {
+            if !new_keys.contains_key(key_id) {
+                changes.push(MetadataChange::new(
+                    MetadataChangeKind::KeyDetached,
+                    format!(
+                        "Key '{}' was removed from vault '{}'",
+                        label,
+                        manifest.label()
+                    ),
+                ));
+            }
+        }
+    }
+    for (id, &j) in &vaults_before {
+        if !vaults_after.contains_key(id) {
+            changes.push(MetadataChange::new(
+                MetadataChangeKind::VaultRemoved,
+                format!("Vault '{}' was deleted", before.manifests[j].label()),
+            ));
+        }
+    }
+
+    // Archive index entries, counted per vault
+    let empty = ArchiveIndex::default();
+    let index_before = before.archive_index.as_ref().unwrap_or(&empty);
+    let index_after = after.archive_index.as_ref().unwrap_or(&empty);
+    let vault_ids: BTreeSet<&String> = index_before
+        .vaults
+        .keys()
+        .chain(index_after.vaults.keys())
+        .collect();
+    for vault_id in vault_ids {
+        let by_id = |entries: &'_ [ArchiveIndexEntry]| -> HashMap<String, ArchiveIndexEntry> {
+            entries
+                .iter()
+                .map(|entry| (entry.archive_id.clone(), entry.clone()))
+                .collect()
+        };
+        let old = by_id(index_before.entries(vault_id));
+        let new = by_id(index_after.entries(vault_id));
+        let added = new.keys().filter(|id| !old.contains_key(*id)).count();
+        let removed = old.keys().filter(|id| !new.contains_key(*id)).count();
+        let updated = new
+            .iter()
+            .filter(|(id, entry)| old.get(*id).is_some_and(|o| o != *entry))
+            .count();
+        let label = after.vault_label(vault_id);
+        let label = if label == vault_id.as_str() {
+            before.vault_label(vault_id)
+        } else {
+            label
+        };
+
+        for (count, kind, verb) in [
+            (added, MetadataChangeKind::ArchivesIndexed, "indexed"),
+            (
+                removed,
+                MetadataChangeKind::ArchivesRemoved,
+                "removed from the index",
+            ),
+            (updated, MetadataChangeKind::ArchivesUpdated, "updated"),
+        ] {
+            if count > 0 {
+                changes.push(MetadataChange::new(
+                    kind,
+                    format!(
+                        "{} archive{} {} for vault '{}'",
+                        count,
+                        if count == 1 { "" } else { "s" },
+                        verb,
+                        label
+                    ),
+                ));
+            }
+        }
+    }
+
+    changes.sort_by(|a, b| (a.kind, &a.description).cmp(&(b.kind, &b.description)));
+    changes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::key_management::shared::KeyEntry;
+    use crate::services::key_management::shared::domain::models::key_lifecycle::KeyLifecycleStatus;
+    use crate::services::shared::infrastructure::DeviceInfo;
+    use crate::services::vault::infrastructure::persistence::{
+        RecipientInfo, RecipientType, SnapshotSource,
+    };
+    use std::path::Path;
+    use tempfile::TempDir;
+
+    struct Fixture {
+        _temp: TempDir,
+        layout: SnapshotLayout,
+        store: MetadataSnapshotStore,
+        journal: MutationJournal,
+    }
+
+    fn fixture() -> Fixture {
+        let temp = TempDir::new().unwrap();
+        let layout = SnapshotLayout {
+            registry: temp.path().join("registry.json"),
+            archive_index: temp.path().join("archive_index.json"),
+            manifests_dir: temp.path().join("vaults"),
+        };
+        fs::create_dir_all(&layout.manifests_dir).unwrap();
+        Fixture {
+            store: MetadataSnapshotStore::at(temp.path().join("snapshots")),
+            journal: MutationJournal::at(temp.path().join("journal")),
+            layout,
+            _temp: temp,
+        }
+    }
+
+    fn passphrase_key(label: &str) -> KeyEntry {
+        KeyEntry::Passphrase {
+            label: label.to_string(),
+            created_at: Utc::now(),
+            last_used: None,
+            public_key: format!("age1{}", label.to_lowercase()),
+            key_filename: format!("{label}.agekey.enc"),
+            lifecycle_status: KeyLifecycleStatus::Active,
+            status_history: vec![],
+            vault_associations: vec!["vault-001".to_string()],
+            deactivated_at: None,
+            previous_lifecycle_status: None,
+            key_location: None,
+            passphrase_policy: 0,
+        }
+    }
+
+    fn manifest(label: &str, key_labels: &[&str]) -> VaultMetadata {
+        let device_info = DeviceInfo {
+            machine_id: "machine-1".to_string(),
+            machine_label: "laptop".to_string(),
+            created_at: Utc::now(),
+            app_version: "2.0.0".to_string(),
+        };
+        let recipients = key_labels
+            .iter()
+            .map(|key| RecipientInfo {
+                key_id: key.to_lowercase(),
+                recipient_type: RecipientType::Passphrase {
+                    key_filename: format!("{key}.agekey.enc"),
+                },
+                public_key: format!("age1{}", key.to_lowercase()),
+                label: key.to_string(),
+                created_at: Utc::now(),
+            })
+            .collect();
+        VaultMetadata::new(
+            "vault-001".to_string(),
+            label.to_string(),
+            None,
+            "Family".to_string(),
+            &device_info,
+            None,
+            recipients,
+            vec![],
+            0,
+            0,
+        )
+    }
+
+    fn write_json(path: &Path, value: &impl serde::Serialize) {
+        fs::write(path, serde_json::to_vec_pretty(value).unwrap()).unwrap();
+    }
+
+    fn write_state(f: &Fixture, key_labels: &[&str], vault_label: &str) {
+        let mut registry = KeyRegistry::new();
+        for label in key_labels {
+            registry
+                .register_key(label.to_lowercase(), passphrase_key(label))
+                .unwrap();
+        }
+        write_json(&f.layout.registry, &registry);
+        write_json(
+            &f.layout.manifests_dir.join("Family.manifest"),
+            &manifest(vault_label, key_labels),
+        );
+    }
+
+    fn capture(f: &Fixture, operation: &str) -> String {
+        let sources = f.layout.sources().unwrap();
+        f.store.capture(operation, &sources, Utc::now()).unwrap().id
+    }
+
+    fn archive(archive_id: &str) -> ArchiveIndexEntry {
+        ArchiveIndexEntry {
+            archive_id: archive_id.to_string(),
+            vault_id: "vault-001".to_string(),
+            archive_name: "Family.age".to_string(),
+            encryption_revision: 1,
+            created_at: Utc::now(),
+            file_count: 3,
+            comment: None,
+            comment_updated_at: None,
+            immutable: false,
+            parity: None,
+            migrated_from: None,
+            prune_marked: false,
+            remapped_from: None,
+            archive_sha256: None,
+            recipients: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_diff_describes_changes_since_snapshot() {
+        let f = fixture();
+        write_state(&f, &["Alice", "Bob"], "Family");
+        let snapshot_id = capture(&f, "update_key_label");
+
+        // Remove Bob, add Carol, rename the vault and index an archive
+        write_state(&f, &["Alice", "Carol"], "Family Documents");
+        let mut index = ArchiveIndex::default();
+        index.record(archive("archive-1"));
+        index.record(archive("archive-2"));
+        write_json(&f.layout.archive_index, &index);
+
+        let diff = diff_in(&f.store, &f.layout, &snapshot_id).unwrap();
+        let described: Vec<(MetadataChangeKind, &str)> = diff
+            .changes
+            .iter()
+            .map(|c| (c.kind, c.description.as_str()))
+            .collect();
+        assert_eq!(
+            described,
+            [
+                (MetadataChangeKind::KeyAdded, "Key 'Carol' was added"),
+                (MetadataChangeKind::KeyRemoved, "Key 'Bob' was removed"),
+                (
+                    MetadataChangeKind::VaultRenamed,
+                    "Vault 'Family' was renamed to 'Family Documents'"
+                ),
+                (
+                    MetadataChangeKind::KeyAttached,
+                    "Key 'Carol' was attached to vault 'Family Documents'"
+                ),
+                (
+                    MetadataChangeKind::KeyDetached,
+                    "Key 'Bob' was removed from vault 'Family Documents'"
+                ),
+                (
+                    MetadataChangeKind::ArchivesIndexed,
+                    "2 archives indexed for vault 'Family Documents'"
+                ),
+            ]
+        );
+        assert!(diff.snapshot.includes_registry);
+        assert!(!diff.snapshot.includes_archive_index);
+        assert_eq!(diff.snapshot.manifest_count, 1);
+    }
+
+    #[test]
+    fn test_restore_reverts_key_removal() {
+        let f = fixture();
+        write_state(&f, &["Alice", "Bob"], "Family");
+        let snapshot_id = capture(&f, "remove_key_from_vault");
+        write_state(&f, &["Alice"], "Family");
+
+        // The typed confirmation is required
+        for confirmation in [None, Some("restore")] {
+            assert!(matches!(
+                restore_in(&f.store, &f.journal, &f.layout, &snapshot_id, confirmation),
+                Err(VaultError::InvalidOperation(_))
+            ));
+        }
+
+        let result = restore_in(
+            &f.store,
+            &f.journal,
+            &f.layout,
+            &snapshot_id,
+            Some(RESTORE_SNAPSHOT_CONFIRMATION),
+        )
+        .unwrap();
+
+        assert_eq!(result.restored_files, 2);
+        assert!(
+            result
+                .reverted
+                .iter()
+                .any(|c| c.kind == MetadataChangeKind::KeyAdded)
+        );
+        let registry: KeyRegistry =
+            serde_json::from_slice(&fs::read(&f.layout.registry).unwrap()).unwrap();
+        assert!(registry.contains_key("bob"));
+        let restored = diff_in(&f.store, &f.layout, &snapshot_id).unwrap();
+        assert!(restored.changes.is_empty());
+
+        // The replaced state was kept as a restore point of its own
+        let undo = result.undo_snapshot_id.unwrap();
+        let undo_diff = diff_in(&f.store, &f.layout, &undo).unwrap();
+        assert_eq!(undo_diff.changes.len(), 2);
+        assert_eq!(list_in(&f.store).unwrap()[0].snapshot_id, undo);
+    }
+
+    #[test]
+    fn test_restore_refuses_paths_outside_layout() {
+        let f = fixture();
+        write_state(&f, &["Alice"], "Family");
+        let outside = f
+            .layout
+            .manifests_dir
+            .parent()
+            .unwrap()
+            .join("elsewhere.json");
+        fs::write(&outside, b"{}").unwrap();
+        let sources = [SnapshotSource {
+            kind: SnapshotFileKind::Registry,
+            path: outside,
+        }];
+        let snapshot_id = f.store.capture("test", &sources, Utc::now()).unwrap().id;
+
+        assert!(matches!(
+            restore_in(
+                &f.store,
+                &f.journal,
+                &f.layout,
+                &snapshot_id,
+                Some(RESTORE_SNAPSHOT_CONFIRMATION)
+            ),
+            Err(VaultError::InvalidOperation(_))
+        ));
+        assert!(matches!(
+            diff_in(&f.store, &f.layout, "missing"),
+            Err(VaultError::NotFound(_))
+        ));
+    }
+}
@@ -1,22 +1,92 @@
+mod archive_patch_service;
+mod archive_repair_service;
+mod archive_service;
 mod bootstrap_service;
+mod clipboard_guard_service;
+mod compatibility_service;
+mod dead_mans_switch_service;
+mod directory_comparison_service;
+mod eject_safety_service;
+mod file_search_service;
+mod hook_service;
+mod inventory_service;
+mod launch_watchdog_service;
+mod maintenance_service;
+mod metadata_snapshot_service;
+mod notification_service;
+mod onboarding_service;
+mod operation_log_service;
+mod output_naming_service;
 mod payload_staging_service;
+mod quarantine_service;
 mod recovery_txt_service;
+mod retention_service;
+mod shared_store_service;
+mod shutdown_service;
+mod statistics_history_service;
+mod storage_quota_service;
+mod transform_settings_service;
 mod vault_bundle_encryption_service;
+mod vault_conflict_service;
+mod vault_item_service;
 mod vault_metadata_service;
+mod vault_order_service;
+mod vault_risk_service;
 pub mod vault_service;
 mod vault_statistics_service;
+mod vault_template_service;
 mod version_service;
 
+pub use archive_patch_service::{ArchivePatchService, PatchLayer, PreparedPatch};
+pub use archive_repair_service::ArchiveRepairService;
+pub use archive_service::ArchiveService;
 pub use bootstrap_service::{BootstrapResult, BootstrapService};
+pub use clipboard_guard_service::{ClipboardBackend, ClipboardCopy, ClipboardGuard};
+pub use compatibility_service::CompatibilityService;
+pub use dead_mans_switch_service::DeadMansSwitchService;
+pub use directory_comparison_service::DirectoryComparisonService;
+pub use eject_safety_service::EjectSafetyService;
+pub use file_search_service::FileSearchService;
+pub use hook_service::{HookService, TEST_HOOK_ARCHIVE_PATH};
+pub use inventory_service::InventoryService;
+pub use launch_watchdog_service::LaunchWatchdog;
+pub use maintenance_service::{
+    MAINTENANCE_CONCURRENCY, MaintenanceExecutor, MaintenanceService, MaintenanceTarget,
+    VaultMaintenanceExecutor,
+};
+pub use metadata_snapshot_service::MetadataSnapshotService;
+pub use notification_service::{
+    Clock, DEFAULT_SNOOZE_DAYS, MAX_SNOOZE_DAYS, NotificationService, SystemClock,
+};
+pub use onboarding_service::OnboardingService;
+pub use operation_log_service::OperationLogService;
+pub use output_naming_service::OutputNamingService;
 pub use payload_staging_service::PayloadStagingService;
+pub use quarantine_service::{QUARANTINE_DIR, QuarantineService};
 pub use recovery_txt_service::RecoveryTxtService;
+pub use retention_service::RetentionService;
+pub use shared_store_service::SharedStoreService;
+pub use shutdown_service::{
+    FORCED_QUIT_TIMEOUT, OperationControl, SHUTDOWN_BLOCKED_EVENT, ShutdownService, exit_allowed,
+};
+pub use statistics_history_service::StatisticsHistoryService;
+pub use storage_quota_service::{
+    QUARANTINE_MIN_AGE_DAYS, StorageLocations, StorageQuotaService, TRASH_DIR, TRASH_RETENTION_DAYS,
+};
+pub use transform_settings_service::TransformSettingsService;
 pub use vault_bundle_encryption_service::{
-    VaultBundleEncryptionInput, VaultBundleEncryptionResult, VaultBundleEncryptionService,
+    MigrationLink, MigrationSource, VaultBundleEncryptionInput, VaultBundleEncryptionResult,
+    VaultBundleEncryptionService,
 };
+pub use vault_conflict_service::{ConflictLocations, VaultConflictService, VaultFileSet};
+pub use vault_item_service::{VaultItemService, link_targets};
 pub use vault_metadata_service::VaultMetadataService;
+pub use vault_order_service::{VaultOrderService, compare_user_order};
+pub use vault_risk_service::VaultRiskService;
 pub use vault_service::VaultService;
 pub use vault_statistics_service::{
     GlobalVaultStatistics, KeyDetail, KeyStatistics, VaultStatistics, VaultStatisticsService,
     VaultStatus,
 };
-pub use version_service::{VersionComparisonResult, VersionComparisonService};
+pub use vault_template_service::{AppliedTemplateSummary, ProtectionStatus, VaultTemplateService};
+pub use version_service::{ManifestDiscrepancy, VersionComparisonResult, VersionComparisonService};
@@ -0,0 +1,1061 @@
+//! Vault Notification Service
+//!
+//! Evaluates per-vault notification rules (stale backups, pending changes,
+//! verification reminders, archives eligible for pruning, dead man's switch
+//! check-ins, reserved YubiKey slots left waiting, operations a forced quit
+//! interrupted) against vault statistics and produces a deduplicated,
+//! prioritized digest. Dismissals are persisted as snoozes in the local vault
+//! settings so they survive restarts. A previous launch that ended before the
+//! interface loaded is reported app-wide, alongside the vaults' entries.
+//!
+//! Rule evaluation is pure over `VaultStatistics`, the local vault settings
+//! and an injected `Clock`, so it never touches archives and is fully
+//! unit-testable. Every rule depends on the clock (retention windows and
+//! check-in deadlines included), so while the system clock is implausible no
+//! rule is evaluated and existing trigger bookkeeping is left alone.
+
+use crate::prelude::*;
+use crate::services::shared::infrastructure::{
+    ClockService, Locale, OperationKind, format_bytes, format_date, format_date_time,
+};
+use crate::services::vault::application::services::{
+    LaunchWatchdog, VaultStatistics, VaultStatisticsService,
+};
+use crate::services::vault::domain::models::{
+    CheckInState, LaunchDiagnostics, LaunchOutcome, NotificationCategory, NotificationPreferences,
+    NotificationSeverity, VaultNotification,
+};
+use crate::services::vault::domain::{VaultError, VaultResult};
+use crate::services::vault::infrastructure::persistence::{
+    APP_LOG_SCOPE, NotificationSnooze, VaultSettings, VaultSettingsRegistry, configured_locale,
+};
+use chrono::{DateTime, Duration, Utc};
+use std::collections::{BTreeMap, HashSet};
+use std::sync::Arc;
+
+/// Snooze length used when the caller doesn't specify one
+pub const DEFAULT_SNOOZE_DAYS: u32 = 7;
+
+/// Longest allowed snooze
+pub const MAX_SNOOZE_DAYS: u32 = 365;
+
+pub use crate::services::shared::infrastructure::{Clock, SystemClock};
+
+/// Outcome of a rule that fired
+struct TriggeredRule {
+    severity: NotificationSeverity,
+    params: BTreeMap<String, String>,
+}
+
+/// Service for the vault notifications digest
+pub struct NotificationService {
+    clock: ClockService,
+    /// Locale of the display parameters (sizes, dates)
+    locale: Locale,
+}
+
+impl NotificationService {
+    pub fn new() -> Self {
+        Self::with_clock_service(ClockService::global().clone()).with_locale(configured_locale())
+    }
+
+    /// Evaluate against `clock` as-is, without plausibility checks
+    pub fn with_clock(clock: Box<dyn Clock>) -> Self {
+        Self::with_clock_service(ClockService::trusted(Arc::from(clock)))
+    }
+
+    pub fn with_clock_service(clock: ClockService) -> Self {
+        Self {
+            clock,
+            locale: Locale::default(),
+        }
+    }
+
+    pub fn with_locale(self, locale: Locale) -> Self {
+        Self { locale, ..self }
+    }
+
+    /// Evaluate all enabled rules across vaults and persist trigger bookkeeping
+    pub fn get_notifications(&self) -> VaultResult<Vec<VaultNotification>> {
+        let statistics = VaultStatisticsService::new()
+            .get_all_vault_statistics()
+            .map_err(|e| VaultError::StorageError(e.to_string()))?;
+        let mut settings = load_settings()?;
+
+        let before = settings.clone();
+        let mut digest = self.digest(&statistics.vault_statistics, &mut settings);
+        if settings != before {
+            save_settings(&settings)?;
+        }
+
+        match LaunchWatchdog::global().previous_failed_launch() {
+            Ok(Some(record)) => {
+                digest.push(self.launch_failed(&record));
+                sort_by_priority(&mut digest);
+            }
+            Ok(None) => {}
+            Err(e) => warn!(error = %e, "Failed to load launch diagnostics"),
+        }
+
+        Ok(digest)
+    }
+
+    /// Snooze a notification for `snooze_days`, returning when it may reappear
+    pub fn dismiss_notification(
+        &self,
+        notification_id: &str,
+        snooze_days: u32,
+    ) -> VaultResult<DateTime<Utc>> {
+        if let Some((NotificationCategory::LaunchFailed, _)) =
+            VaultNotification::parse_id(notification_id)
+        {
+            LaunchWatchdog::global().clear_failed_launch()?;
+            return Ok(self.clock.now());
+        }
+
+        let mut settings = load_settings()?;
+        let until = self.dismiss(&mut settings, notification_id, snooze_days)?;
+        save_settings(&settings)?;
+        Ok(until)
+    }
+
+    /// Notification preferences for a vault (defaults if never set)
+    pub fn get_preferences(&self, vault_id: &str) -> VaultResult<NotificationPreferences> {
+        Ok(load_settings()?.get(vault_id).notification_preferences)
+    }
+
+    /// Replace a vault's notification preferences
+    pub fn update_preferences(
+        &self,
+        vault_id: &str,
+        preferences: NotificationPreferences,
+    ) -> VaultResult<NotificationPreferences> {
+        let mut settings = load_settings()?;
+        settings.entry(vault_id).notification_preferences = preferences.clone();
+        save_settings(&settings)?;
+
+        info!(vault_id, "Updated notification preferences");
+        Ok(preferences)
+    }
+
+    /// Build the digest for the given statistics
+    ///
+    /// Records when each notification first triggered, drops bookkeeping for
+    /// conditions that have cleared, and hides snoozed notifications.
+    pub fn digest(
+        &self,
+        statistics: &[VaultStatistics],
+        settings: &mut VaultSettingsRegistry,
+    ) -> Vec<VaultNotification> {
+        if !self.clock.is_reliable() {
+            warn!("System clock is unreliable; skipping age-based notifications");
+            return Vec::new();
+        }
+
+        let now = self.clock.now();
+        let mut seen = HashSet::new();
+        let mut notifications = Vec::new();
+
+        for stats in statistics {
+            if !seen.insert(stats.vault_id.as_str()) {
+                continue;
+            }
+
+            let vault_settings = settings.entry(&stats.vault_id);
+            for category in NotificationCategory::ALL {
+                let triggered = if vault_settings.notification_preferences.is_enabled(category) {
+                    evaluate_rule(category, stats, vault_settings, now, self.locale)
+                } else {
+                    None
+                };
+
+                match triggered {
+                    Some(rule) => notifications.extend(Self::record_trigger(
+                        vault_settings,
+                        category,
+                        stats,
+                        rule,
+                        now,
+                    )),
+                    None => {
+                        // Condition cleared - the next occurrence starts fresh
+                        vault_settings
+                            .notification_first_triggered
+                            .remove(&category);
+                        vault_settings.notification_snoozes.remove(&category);
+                        if category == NotificationCategory::InterruptedOperation {
+                            vault_settings.interrupted_operations.clear();
+                        }
+                    }
+                }
+            }
+        }
+
+        settings
+            .vaults
+            .retain(|_, s| *s != VaultSettings::default());
+        sort_by_priority(&mut notifications);
+        notifications
+    }
+
+    /// Snooze a notification in `settings`
+    ///
+    /// Interrupted-operation notifications are cleared rather than snoozed,
+    /// since the interruption is in the past.
+    pub fn dismiss(
+        &self,
+        settings: &mut VaultSettingsRegistry,
+        notification_id: &str,
+        snooze_days: u32,
+    ) -> VaultResult<DateTime<Utc>> {
+        let (category, vault_id) =
+            VaultNotification::parse_id(notification_id).ok_or_else(|| {
+                VaultError::InvalidOperation(format!(
+                    "Invalid notification ID '{}'",
+                    notification_id
+                ))
+            })?;
+
+        if snooze_days == 0 || snooze_days > MAX_SNOOZE_DAYS {
+            return Err(VaultError::InvalidOperation(format!(
+                "Snooze must be between 1 and {} days",
+                MAX_SNOOZE_DAYS
+            )));
+        }
+
+        let now = self.clock.now();
+        if category == NotificationCategory::InterruptedOperation {
+            let vault_settings = settings.entry(vault_id);
+            vault_settings.interrupted_operations.clear();
+            vault_settings
+                .notification_first_triggered
+                .remove(&category);
+            info!(notification_id, "Cleared interrupted operations");
+            return Ok(now);
+        }
+
+        let snoozed_until = now + Duration::days(i64::from(snooze_days));
+        settings.entry(vault_id).notification_snoozes.insert(
+            category,
+            NotificationSnooze {
+                dismissed_at: now,
+                snoozed_until,
+            },
+        );
+
+        info!(notification_id, snooze_days, "Snoozed vault notification");
+        Ok(snoozed_until)
+    }
+
+    /// App-wide entry for a launch that ended before the interface loaded
+    ///
+    /// Not snoozed; it stays until dismissed, which forgets the record.
+    pub fn launch_failed(&self, record: &LaunchDiagnostics) -> VaultNotification {
+        let category = NotificationCategory::LaunchFailed;
+        let severity = match record.outcome {
+            LaunchOutcome::NoHandshake => NotificationSeverity::Warning,
+            LaunchOutcome::LateHandshake => NotificationSeverity::Info,
+        };
+
+        VaultNotification {
+            id: VaultNotification::make_id(category, APP_LOG_SCOPE),
+            category,
+            vault_id: APP_LOG_SCOPE.to_string(),
+            vault_name: String::new(),
+            message_key: category.message_key(),
+            params: params([
+                ("outcome", record.outcome.as_str().to_string()),
+                ("started_at", record.started_at.to_rfc3339()),
+                (
+                    "started_on",
+                    format_date_time(record.started_at, self.locale),
+                ),
+                ("app_version", record.app_version.clone()),
+                ("timeout", record.timeout.display()),
+            ]),
+            severity,
+            first_triggered_at: record.recorded_at,
+        }
+    }
+
+    fn record_trigger(
+        vault_settings: &mut VaultSettings,
+        category: NotificationCategory,
+        stats: &VaultStatistics,
+        rule: TriggeredRule,
+        now: DateTime<Utc>,
+    ) -> Option<VaultNotification> {
+        let first_triggered_at = *vault_settings
+            .notification_first_triggered
+            .entry(category)
+            .or_insert(now);
+
+        if vault_settings.is_snoozed(category, now) {
+            return None;
+        }
+        vault_settings.notification_snoozes.remove(&category);
+
+        Some(VaultNotification {
+            id: VaultNotification::make_id(category, &stats.vault_id),
+            category,
+            vault_id: stats.vault_id.clone(),
+            vault_name: stats.vault_name.clone(),
+            message_key: category.message_key(),
+            params: rule.params,
+            severity: rule.severity,
+            first_triggered_at,
+        })
+    }
+}
+
+impl Default for NotificationService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn load_settings() -> VaultResult<VaultSettingsRegistry> {
+    VaultSettingsRegistry::load().map_err(|e| VaultError::StorageError(e.to_string()))
+}
+
+fn save_settings(settings: &VaultSettingsRegistry) -> VaultResult<()> {
+    settings
+        .save()
+        .map_err(|e| VaultError::StorageError(e.to_string()))
+}
+
+/// Highest severity first, then category order, then oldest first
+fn sort_by_priority(notifications: &mut [VaultNotification]) {
+    notifications.sort_by(|a, b| {
+        b.severity
+            .cmp(&a.severity)
+            .then(a.category.cmp(&b.category))
+            .then(a.first_triggered_at.cmp(&b.first_triggered_at))
+            .then(a.vault_name.cmp(&b.vault_name))
+    });
+}
+
+fn evaluate_rule(
+    category: NotificationCategory,
+    stats: &VaultStatistics,
+    settings: &VaultSettings,
+    now: DateTime<Utc>,
+    locale: Locale,
+) -> Option<TriggeredRule> {
+    let preferences = &settings.notification_preferences;
+    match category {
+        NotificationCategory::StaleBackup => stale_backup(stats, preferences, now, locale),
+        NotificationCategory::PendingChanges => pending_changes(stats, preferences, now),
+        NotificationCategory::VerificationReminder => {
+            verification_reminder(stats, preferences, now)
+        }
+        NotificationCategory::PruneEligible => prune_eligible(stats, locale),
+        NotificationCategory::CheckInDue => {
+            check_in_reminder(stats, settings, now, CheckInState::DueSoon, locale)
+        }
+        NotificationCategory::CheckInLapsed => {
+            check_in_reminder(stats, settings, now, CheckInState::Lapsed, locale)
+        }
+        NotificationCategory::PendingHardwareKey => pending_hardware_key(stats, preferences, now),
+        NotificationCategory::InterruptedOperation => interrupted_operation(stats, settings),
+        // App-wide, see `NotificationService::launch_failed`
+        NotificationCategory::LaunchFailed => None,
+    }
+}
+
+/// Last encryption is older than the threshold; critical at twice the threshold
+fn stale_backup(
+    stats: &VaultStatistics,
+    preferences: &NotificationPreferences,
+    now: DateTime<Utc>,
+    locale: Locale,
+) -> Option<TriggeredRule> {
+    // Never-encrypted vaults are not stale - they are simply not started yet
+    let last_encrypted_at = stats.last_encrypted_at?;
+    let days = (now - last_encrypted_at).num_days();
+    let threshold = i64::from(preferences.stale_backup_days);
+    if days <= threshold {
+        return None;
+    }
+
+    let severity = if days >= threshold * 2 {
+        NotificationSeverity::Critical
+    } else {
+        NotificationSeverity::Warning
+    };
+
+    Some(TriggeredRule {
+        severity,
+        params: params([
+            ("vault_name", stats.vault_name.clone()),
+            ("days_since_backup", days.to_string()),
+            ("last_backup_on", format_date(last_encrypted_at, locale)),
+            ("threshold_days", threshold.to_string()),
+        ]),
+    })
+}
+
+/// Keys attached since the last encryption are not protecting the archive yet
+fn pending_changes(
+    stats: &VaultStatistics,
+    preferences: &NotificationPreferences,
+    now: DateTime<Utc>,
+) -> Option<TriggeredRule> {
+    let pending: Vec<_> = stats
+        .key_statistics
+        .key_details
+        .iter()
+        .filter(|key| {
+            stats
+                .last_encrypted_at
+                .is_none_or(|encrypted_at| key.created_at > encrypted_at)
+        })
+        .collect();
+
+    let pending_since = pending.iter().map(|key| key.created_at).min()?;
+    let days = (now - pending_since).num_days();
+    if days < i64::from(preferences.pending_changes_days) {
+        return None;
+    }
+
+    Some(TriggeredRule {
+        severity: NotificationSeverity::Warning,
+        params: params([
+            ("vault_name", stats.vault_name.clone()),
+            ("pending_key_count", pending.len().to_string()),
+            ("days_pending", days.to_string()),
+        ]),
+    })
+}
+
+/// No key has decrypted the vault recently
+fn verification_reminder(
+    stats: &VaultStatistics,
+    preferences: &NotificationPreferences,
+    now: DateTime<Utc>,
+) -> Option<TriggeredRule> {
+    let last_encrypted_at = stats.last_encrypted_at?;
+    let last_verified_at = stats
+        .key_statistics
+        .key_details
+        .iter()
+        .filter_map(|key| key.last_used)
+        .max();
+
+    let since = last_verified_at.unwrap_or(last_encrypted_at);
+    let days = (now - since).num_days();
+    if days < i64::from(preferences.verification_reminder_days) {
+        return None;
+    }
+
+    Some(TriggeredRule {
+        severity: NotificationSeverity::Info,
+        params: params([
+            ("vault_name", stats.vault_name.clone()),
+            ("days_since_verified", days.to_string()),
+            ("never_verified", last_verified_at.is_none().to_string()),
+        ]),
+    })
+}
+
+/// The retention policy would prune some archives
+fn prune_eligible(stats: &VaultStatistics, locale: Locale) -> Option<TriggeredRule> {
+    let retention = stats.retention;
+    if retention.eligible_count == 0 {
+        return None;
+    }
+
+    Some(TriggeredRule {
+        severity: NotificationSeverity::Info,
+        params: params([
+            ("vault_name", stats.vault_name.clone()),
+            ("eligible_count", retention.eligible_count.to_string()),
+            (
+                "reclaimable",
+                format_bytes(retention.reclaimable.bytes(), locale),
+            ),
+        ]),
+    })
+}
+
+/// The dead man's switch is in `state`; lapsed switches are critical
+fn check_in_reminder(
+    stats: &VaultStatistics,
+    settings: &VaultSettings,
+    now: DateTime<Utc>,
+    state: CheckInState,
+    locale: Locale,
+) -> Option<TriggeredRule> {
+    let status = settings.dead_mans_switch.as_ref()?.status(Some(now));
+    if status.state != state {
+        return None;
+    }
+
+    let (severity, days) = match state {
+        CheckInState::Lapsed => (
+            NotificationSeverity::Critical,
+            ("days_overdue", status.days_overdue?),
+        ),
+        _ => (
+            NotificationSeverity::Warning,
+            ("days_until_due", status.days_until_due?),
+        ),
+    };
+
+    Some(TriggeredRule {
+        severity,
+        params: params([
+            ("vault_name", stats.vault_name.clone()),
+            (days.0, days.1.to_string()),
+            ("due_at", status.due_at.to_rfc3339()),
+            ("due_on", format_date(status.due_at, locale)),
+        ]),
+    })
+}
+
+/// A reserved YubiKey slot has waited longer than the threshold for its hardware
+fn pending_hardware_key(
+    stats: &VaultStatistics,
+    preferences: &NotificationPreferences,
+    now: DateTime<Utc>,
+) -> Option<TriggeredRule> {
+    let pending = &stats.key_statistics.pending_yubikeys;
+    let oldest = pending.iter().min_by_key(|key| key.created_at)?;
+    let days = (now - oldest.created_at).num_days();
+    if days < i64::from(preferences.pending_hardware_key_days) {
+        return None;
+    }
+
+    Some(TriggeredRule {
+        severity: NotificationSeverity::Warning,
+        params: params([
+            ("vault_name", stats.vault_name.clone()),
+            ("pending_key_count", pending.len().to_string()),
+            ("oldest_label", oldest.label.clone()),
+            ("days_pending", days.to_string()),
+        ]),
+    })
+}
+
+/// A forced quit cut an operation short; an interrupted encryption stops
+/// counting once the vault has been encrypted again
+fn interrupted_operation(
+    stats: &VaultStatistics,
+    settings: &VaultSettings,
+) -> Option<TriggeredRule> {
+    let outstanding: Vec<_> = settings
+        .interrupted_operations
+        .iter()
+        .filter(|op| {
+            op.operation != OperationKind::Encryption
+                || stats
+                    .last_encrypted_at
+                    .is_none_or(|encrypted_at| encrypted_at < op.interrupted_at)
+        })
+        .collect();
+    let latest = outstanding.iter().max_by_key(|op| op.interrupted_at)?;
+
+    Some(TriggeredRule {
+        severity: NotificationSeverity::Warning,
+        params: params([
+            ("vault_name", stats.vault_name.clone()),
+            ("operation", latest.operation.to_string()),
+            ("interrupted_at", latest.interrupted_at.to_rfc3339()),
+            ("interrupted_count", outstanding.len().to_string()),
+            (
+                "quarantined_files",
+                latest.quarantined_files.unwrap_or_default().to_string(),
+            ),
+        ]),
+    })
+}
+
+fn params<const N: usize>(entries: [(&str, String); N]) -> BTreeMap<String, String> {
+    entries
+        .into_iter()
+        .map(|(key, value)| (key.to_string(), value))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::key_management::shared::domain::models::key_lifecycle::KeyLifecycleStatus;
+    use crate::services::vault::application::services::{KeyDetail, KeyStatistics, VaultStatus};
+    use crate::services::vault::domain::models::RetentionSummary;
+    use crate::types::ByteSize;
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Clone)]
+    struct TestClock(Arc<Mutex<DateTime<Utc>>>);
+
+    impl TestClock {
+        fn at(now: DateTime<Utc>) -> Self {
+            Self(Arc::new(Mutex::new(now)))
+        }
+
+        fn advance_days(&self, days: i64) {
+            *self.0.lock().unwrap() += Duration::days(days);
+        }
+    }
+
+    impl Clock for TestClock {
+        fn now(&self) -> DateTime<Utc> {
+            *self.0.lock().unwrap()
+        }
+    }
+
+    fn base_time() -> DateTime<Utc> {
+        "2025-06-01T12:00:00Z".parse().unwrap()
+    }
+
+    fn key(created_at: DateTime<Utc>, last_used: Option<DateTime<Utc>>) -> KeyDetail {
+        KeyDetail {
+            key_id: "key-1".to_string(),
+            label: "Key 1".to_string(),
+            key_type: "passphrase".to_string(),
+            lifecycle_status: KeyLifecycleStatus::Active,
+            created_at,
+            last_used,
+            is_available: true,
+        }
+    }
+
+    fn stats(
+        vault_id: &str,
+        last_encrypted_days_ago: Option<i64>,
+        keys: Vec<KeyDetail>,
+    ) -> VaultStatistics {
+        let now = base_time();
+        VaultStatistics {
+            vault_id: vault_id.to_string(),
+            vault_name: format!("Vault {}", vault_id),
+            description: None,
+            status: VaultStatus::Active,
+            encryption_count: u32::from(last_encrypted_days_ago.is_some()),
+            created_at: now - Duration::days(400),
+            last_encrypted_at: last_encrypted_days_ago.map(|d| now - Duration::days(d)),
+            last_encrypted_by: None,
+            file_count: 1,
+            total_size_bytes: ByteSize(1),
+            key_statistics: KeyStatistics {
+                total_keys: keys.len(),
+                active_keys: keys.len(),
+                orphaned_keys: 0,
+                passphrase_keys: keys.len(),
+                yubikey_keys: 0,
+                key_details: keys,
+                pending_yubikeys: vec![],
+            },
+            archive_exists: true,
+            manifest_exists: true,
+            item_statistics: Default::default(),
+            retention: Default::default(),
+            trend: Default::default(),
+            format_hints: None,
+        }
+    }
+
+    fn recently_verified() -> Vec<KeyDetail> {
+        let now = base_time();
+        vec![key(
+            now - Duration::days(500),
+            Some(now - Duration::days(1)),
+        )]
+    }
+
+    #[test]
+    fn test_fresh_vault_has_no_notifications() {
+        let service = NotificationService::with_clock(Box::new(TestClock::at(base_time())));
+        let mut settings = VaultSettingsRegistry::default();
+
+        let digest = service.digest(&[stats("a", Some(5), recently_verified())], &mut settings);
+
+        assert!(digest.is_empty());
+        assert!(settings.vaults.is_empty());
+    }
+
+    #[test]
+    fn test_bad_clock_suppresses_age_based_rules() {
+        use crate::services::shared::infrastructure::clock::testing::{FakeClock, service};
+
+        let clock = FakeClock::at("2025-06-01T12:00:00Z");
+        let notifications = NotificationService::with_clock_service(service(&clock));
+        let mut settings = VaultSettingsRegistry::default();
+        let vaults = [stats("a", Some(60), recently_verified())];
+
+        let digest = notifications.digest(&vaults, &mut settings);
+        assert_eq!(digest.len(), 1);
+        let bookkeeping = settings.clone();
+
+        // Clock reset to 1970: no notifications, and first-triggered times survive
+        clock.set_wall("1970-01-01T00:00:00Z");
+        assert!(notifications.digest(&vaults, &mut settings).is_empty());
+        assert_eq!(settings, bookkeeping);
+
+        clock.set_wall("2040-01-01T00:00:00Z");
+        assert!(notifications.digest(&vaults, &mut settings).is_empty());
+    }
+
+    #[test]
+    fn test_stale_backup_severity_escalates() {
+        let service = NotificationService::with_clock(Box::new(TestClock::at(base_time())));
+        let mut settings = VaultSettingsRegistry::default();
+
+        let digest = service.digest(
+            &[
+                stats("warn", Some(31), recently_verified()),
+                stats("crit", Some(60), recently_verified()),
+            ],
+            &mut settings,
+        );
+
+        assert_eq!(digest.len(), 2);
+        assert_eq!(digest[0].vault_id, "crit");
+        assert_eq!(digest[0].severity, NotificationSeverity::Critical);
+        assert_eq!(digest[1].severity, NotificationSeverity::Warning);
+        assert_eq!(digest[1].message_key, "notifications.stale_backup");
+        assert_eq!(digest[1].params["days_since_backup"], "31");
+    }
+
+    #[test]
+    fn test_priority_ordering() {
+        let now = base_time();
+        let service = NotificationService::with_clock(Box::new(TestClock::at(now)));
+        let mut settings = VaultSettingsRegistry::default();
+
+        let digest = service.digest(
+            &[
+                // Verification reminder only (info)
+                stats(
+                    "verify",
+                    Some(10),
+                    vec![key(
+                        now - Duration::days(500),
+                        Some(now - Duration::days(120)),
+                    )],
+                ),
+                // Key attached after last encryption (warning)
+                stats(
+                    "pending",
+                    Some(10),
+                    vec![key(now - Duration::days(2), Some(now))],
+                ),
+                // Stale backup (critical) plus verification reminder (info)
+                stats(
+                    "stale",
+                    Some(100),
+                    vec![key(now - Duration::days(500), None)],
+                ),
+            ],
+            &mut settings,
+        );
+
+        let order: Vec<_> = digest.iter().map(|n| n.id.as_str()).collect();
+        assert_eq!(
+            order,
+            vec![
+                "stale_backup:stale",
+                "pending_changes:pending",
+                "verification_reminder:stale",
+                "verification_reminder:verify",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_duplicate_vaults_are_deduplicated() {
+        let service = NotificationService::with_clock(Box::new(TestClock::at(base_time())));
+        let mut settings = VaultSettingsRegistry::default();
+        let vault = stats("dup", Some(45), recently_verified());
+
+        let digest = service.digest(&[vault.clone(), vault], &mut settings);
+
+        assert_eq!(digest.len(), 1);
+    }
+
+    #[test]
+    fn test_disabled_category_is_skipped() {
+        let service = NotificationService::with_clock(Box::new(TestClock::at(base_time())));
+        let mut settings = VaultSettingsRegistry::default();
+        settings
+            .entry("a")
+            .notification_preferences
+            .stale_backup_enabled = false;
+
+        let digest = service.digest(&[stats("a", Some(45), recently_verified())], &mut settings);
+
+        assert!(digest.is_empty());
+    }
+
+    #[test]
+    fn test_snooze_expiry() {
+        let clock = TestClock::at(base_time());
+        let service = NotificationService::with_clock(Box::new(clock.clone()));
+        let mut settings = VaultSettingsRegistry::default();
+        let vaults = [stats("a", Some(40), recently_verified())];
+
+        let digest = service.digest(&vaults, &mut settings);
+        assert_eq!(digest.len(), 1);
+        let first_triggered_at = digest[0].first_triggered_at;
+
+        service.dismiss(&mut settings, &digest[0].id, 7).unwrap();
+
+        // Still snoozed partway through
+        clock.advance_days(3);
+        assert!(service.digest(&vaults, &mut settings).is_empty());
+
+        // Reappears once the snooze expires, keeping its original trigger time
+        clock.advance_days(5);
+        let digest = service.digest(&vaults, &mut settings);
+        assert_eq!(digest.len(), 1);
+        assert_eq!(digest[0].first_triggered_at, first_triggered_at);
+        assert!(settings.get("a").notification_snoozes.is_empty());
+    }
+
+    #[test]
+    fn test_prune_eligible_archives_are_reported() {
+        let service = NotificationService::with_clock(Box::new(TestClock::at(base_time())));
+        let mut settings = VaultSettingsRegistry::default();
+        let mut vault = stats("a", Some(5), recently_verified());
+        vault.retention = RetentionSummary {
+            eligible_count: 4,
+            reclaimable: ByteSize(12 * 1024 * 1024 * 1024),
+        };
+
+        let digest = service.digest(&[vault], &mut settings);
+
+        assert_eq!(digest.len(), 1);
+        assert_eq!(digest[0].id, "prune_eligible:a");
+        assert_eq!(digest[0].severity, NotificationSeverity::Info);
+        assert_eq!(digest[0].params["eligible_count"], "4");
+        assert_eq!(digest[0].params["reclaimable"], "12.9 GB");
+    }
+
+    #[test]
+    fn test_display_params_follow_the_locale() {
+        let service = NotificationService::with_clock(Box::new(TestClock::at(base_time())))
+            .with_locale(Locale::De);
+        let mut settings = VaultSettingsRegistry::default();
+        let mut vault = stats("a", Some(45), recently_verified());
+        vault.retention = RetentionSummary {
+            eligible_count: 1,
+            reclaimable: ByteSize(2_300_000_000),
+        };
+
+        let digest = service.digest(&[vault], &mut settings);
+
+        let params = |category| {
+            &digest
+                .iter()
+                .find(|n| n.category == category)
+                .unwrap()
+                .params
+        };
+        // Raw values stay as they were; only the display ones are localized
+        let stale = params(NotificationCategory::StaleBackup);
+        assert_eq!(stale["days_since_backup"], "45");
+        assert_eq!(stale["last_backup_on"], "17. Apr. 2025");
+        assert_eq!(
+            params(NotificationCategory::PruneEligible)["reclaimable"],
+            "2,3 GB"
+        );
+    }
+
+    #[test]
+    fn test_check_in_reminders_escalate_once_lapsed() {
+        use crate::services::vault::domain::models::{DeadMansSwitch, DeadMansSwitchInput};
+
+        let clock = TestClock::at(base_time());
+        let service = NotificationService::with_clock(Box::new(clock.clone()));
+        let mut settings = VaultSettingsRegistry::default();
+        settings.entry("a").dead_mans_switch = Some(DeadMansSwitch::new(
+            DeadMansSwitchInput {
+                check_in_interval_days: 30,
+                notify_before_days: 5,
+                lapsed_message: "Open the safe".to_string(),
+                lapsed_message_translations: Default::default(),
+            },
+            base_time() - Duration::days(20),
+        ));
+        let vaults = [stats("a", Some(5), recently_verified())];
+
+        assert!(service.digest(&vaults, &mut settings).is_empty());
+
+        clock.advance_days(7);
+        let digest = service.digest(&vaults, &mut settings);
+        assert_eq!(digest.len(), 1);
+        assert_eq!(digest[0].id, "check_in_due:a");
+        assert_eq!(digest[0].severity, NotificationSeverity::Warning);
+        assert_eq!(digest[0].params["days_until_due"], "3");
+
+        // Snoozing the reminder doesn't hide the lapse
+        service
+            .dismiss(&mut settings, "check_in_due:a", 30)
+            .unwrap();
+        clock.advance_days(15);
+        let digest = service.digest(&vaults, &mut settings);
+        assert_eq!(digest.len(), 1);
+        assert_eq!(digest[0].id, "check_in_lapsed:a");
+        assert_eq!(digest[0].severity, NotificationSeverity::Critical);
+        assert_eq!(digest[0].params["days_overdue"], "12");
+    }
+
+    #[test]
+    fn test_reserved_yubikey_reported_after_threshold() {
+        let clock = TestClock::at(base_time());
+        let service = NotificationService::with_clock(Box::new(clock.clone()));
+        let mut settings = VaultSettingsRegistry::default();
+        let mut vault = stats("a", Some(5), recently_verified());
+        vault.key_statistics.pending_yubikeys = vec![KeyDetail {
+            key_id: "yubikey-pending".to_string(),
+            label: "Backup YubiKey".to_string(),
+            key_type: "yubikey_pending".to_string(),
+            lifecycle_status: KeyLifecycleStatus::PreActivation,
+            created_at: base_time() - Duration::days(3),
+            last_used: None,
+            is_available: false,
+        }];
+        let vaults = [vault];
+
+        assert!(service.digest(&vaults, &mut settings).is_empty());
+
+        clock.advance_days(4);
+        let digest = service.digest(&vaults, &mut settings);
+        assert_eq!(digest.len(), 1);
+        assert_eq!(digest[0].id, "pending_hardware_key:a");
+        assert_eq!(digest[0].params["oldest_label"], "Backup YubiKey");
+        assert_eq!(digest[0].params["days_pending"], "7");
+
+        // The threshold is a per-vault preference
+        settings
+            .entry("a")
+            .notification_preferences
+            .pending_hardware_key_days = 30;
+        assert!(service.digest(&vaults, &mut settings).is_empty());
+    }
+
+    #[test]
+    fn test_cleared_condition_resets_snooze() {
+        let clock = TestClock::at(base_time());
+        let service = NotificationService::with_clock(Box::new(clock.clone()));
+        let mut settings = VaultSettingsRegistry::default();
+
+        service.digest(&[stats("a", Some(40), recently_verified())], &mut settings);
+        service
+            .dismiss(&mut settings, "stale_backup:a", 30)
+            .unwrap();
+
+        // Vault re-encrypted - condition clears and bookkeeping is dropped
+        service.digest(&[stats("a", Some(0), recently_verified())], &mut settings);
+        assert!(settings.vaults.is_empty());
+    }
+
+    #[test]
+    fn test_dismiss_rejects_invalid_input() {
+        let service = NotificationService::with_clock(Box::new(TestClock::at(base_time())));
+        let mut settings = VaultSettingsRegistry::default();
+
+        assert!(service.dismiss(&mut settings, "bogus", 7).is_err());
+        assert!(service.dismiss(&mut settings, "stale_backup:a", 0).is_err());
+        assert!(
+            service
+                .dismiss(&mut settings, "stale_backup:a", MAX_SNOOZE_DAYS + 1)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_interrupted_operations_until_rerun_or_dismissed() {
+        use crate::services::vault::domain::models::InterruptedOperation;
+
+        let service = NotificationService::with_clock(Box::new(TestClock::at(base_time())));
+        let mut settings = VaultSettingsRegistry::default();
+        let interrupted = |operation, days_ago| InterruptedOperation {
+            operation,
+            interrupted_at: base_time() - Duration::days(days_ago),
+            stopped_in_time: true,
+            quarantined_files: Some(1),
+        };
+        settings.entry("a").interrupted_operations = vec![
+            interrupted(OperationKind::Encryption, 2),
+            interrupted(OperationKind::BatchDecryption, 3),
+        ];
+        settings.entry("b").interrupted_operations =
+            vec![interrupted(OperationKind::Encryption, 2)];
+
+        let digest = service.digest(
+            &[
+                stats("a", Some(10), recently_verified()),
+                stats("b", Some(10), recently_verified()),
+            ],
+            &mut settings,
+        );
+        assert_eq!(digest.len(), 2);
+        let a = digest.iter().find(|n| n.vault_id == "a").unwrap();
+        assert_eq!(a.category, NotificationCategory::InterruptedOperation);
+        assert_eq!(a.params["operation"], "encryption");
+        assert_eq!(a.params["interrupted_count"], "2");
+        assert_eq!(a.params["quarantined_files"], "1");
+
+        // Re-running the encryption settles it; the decryption stays
+        let digest = service.digest(
+            &[
+                stats("a", Some(1), recently_verified()),
+                stats("b", Some(1), recently_verified()),
+            ],
+            &mut settings,
+        );
+        assert_eq!(digest.len(), 1);
+        assert_eq!(digest[0].params["operation"], "batch decryption");
+        assert!(!settings.vaults.contains_key("b"));
+
+        // Dismissing clears it for good instead of snoozing
+        service
+            .dismiss(&mut settings, "interrupted_operation:a", 7)
+            .unwrap();
+        assert!(settings.get("a").interrupted_operations.is_empty());
+        assert!(
+            service
+                .digest(&[stats("a", Some(1), recently_verified())], &mut settings)
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_failed_launch_is_reported_app_wide() {
+        use crate::services::vault::domain::models::LAUNCH_DIAGNOSTICS_SCHEMA;
+        use crate::types::DurationMs;
+
+        let service = NotificationService::with_clock(Box::new(TestClock::at(base_time())));
+        let mut record = LaunchDiagnostics {
+            schema: LAUNCH_DIAGNOSTICS_SCHEMA.to_string(),
+            app_version: "0.9.0".to_string(),
+            os: "macos".to_string(),
+            arch: "aarch64".to_string(),
+            webview_version: None,
+            bindings_hash: None,
+            started_at: base_time() - Duration::days(1),
+            timeout: DurationMs::from_secs(30),
+            recorded_at: base_time() - Duration::days(1),
+            outcome: LaunchOutcome::NoHandshake,
+            handshake: None,
+            navigation_events: Vec::new(),
+        };
+
+        let notification = service.launch_failed(&record);
+        assert_eq!(notification.id, "launch_failed:app");
+        assert_eq!(
+            VaultNotification::parse_id(&notification.id),
+            Some((NotificationCategory::LaunchFailed, APP_LOG_SCOPE))
+        );
+        assert_eq!(notification.severity, NotificationSeverity::Warning);
+        assert_eq!(notification.params["outcome"], "no_handshake");
+
+        record.outcome = LaunchOutcome::LateHandshake;
+        assert_eq!(
+            service.launch_failed(&record).severity,
+            NotificationSeverity::Info
+        );
+    }
+}
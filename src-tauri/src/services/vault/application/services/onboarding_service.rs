@@ -0,0 +1,223 @@
+//! Onboarding Service
+//!
+//! Derives first-run milestone completion from vault manifests, the key
+//! registry and the archive index, so the UI doesn't have to piece it
+//! together from several commands. Only reads small local files, so it's
+//! cheap enough to run on every start and after every relevant operation.
+
+use crate::prelude::*;
+use crate::services::key_management::shared::KeyRegistry;
+use crate::services::key_management::shared::domain::models::key_lifecycle::KeyLifecycleStatus;
+use crate::services::vault::domain::models::{MilestoneStatus, OnboardingFacts, OnboardingStatus};
+use crate::services::vault::domain::{VaultError, VaultResult};
+use crate::services::vault::infrastructure::VaultRepository;
+use crate::services::vault::infrastructure::persistence::{ArchiveIndex, OnboardingProgress};
+
+/// Service for the first-run onboarding checklist
+#[derive(Debug)]
+pub struct OnboardingService {
+    repository: VaultRepository,
+}
+
+impl OnboardingService {
+    pub fn new() -> Self {
+        Self {
+            repository: VaultRepository::new(),
+        }
+    }
+
+    /// Current checklist plus any milestones completed since the last check
+    ///
+    /// Newly completed milestones are cached, so each one is reported as new
+    /// exactly once.
+    pub async fn get_status(&self) -> VaultResult<(OnboardingStatus, Vec<MilestoneStatus>)> {
+        let facts = self.collect_facts().await?;
+        let mut progress = OnboardingProgress::load()
+            .map_err(|e| VaultError::StorageError(format!("Failed to load onboarding: {e}")))?;
+
+        let (status, newly_completed) = Self::evaluate(&facts, &mut progress);
+        if !newly_completed.is_empty() {
+            progress
+                .save()
+                .map_err(|e| VaultError::StorageError(format!("Failed to save onboarding: {e}")))?;
+            info!(
+                count = newly_completed.len(),
+                "Onboarding milestones completed"
+            );
+        }
+
+        Ok((status, newly_completed))
+    }
+
+    /// Merge derived milestones into the cached progress
+    pub fn evaluate(
+        facts: &OnboardingFacts,
+        progress: &mut OnboardingProgress,
+    ) -> (OnboardingStatus, Vec<MilestoneStatus>) {
+        let (status, newly_completed) =
+            OnboardingStatus::merge(&progress.completed, &facts.derive());
+        for milestone in &newly_completed {
+            if let Some(at) = milestone.completed_at {
+                progress.completed.insert(milestone.milestone, at);
+            }
+        }
+        (status, newly_completed)
+    }
+
+    async fn collect_facts(&self) -> VaultResult<OnboardingFacts> {
+        let vaults = self.repository.list_vaults().await?;
+        let registry = KeyRegistry::load()
+            .map_err(|e| VaultError::StorageError(format!("Failed to load registry: {e}")))?;
+        let archives = ArchiveIndex::load()
+            .map_err(|e| VaultError::StorageError(format!("Failed to load archive index: {e}")))?;
+
+        let active_keys = registry
+            .keys
+            .values()
+            .filter(|entry| entry.lifecycle_status() == KeyLifecycleStatus::Active)
+            .map(|entry| {
+                let key_type = if entry.is_yubikey() {
+                    "yubikey"
+                } else if entry.is_recipient() {
+                    "recipient"
+                } else {
+                    "passphrase"
+                };
+                (key_type.to_string(), entry.created_at())
+            })
+            .collect();
+
+        let encryptions = archives
+            .vaults
+            .values()
+            .flatten()
+            .map(|entry| entry.created_at)
+            .chain(vaults.iter().filter_map(|vault| vault.last_encrypted_at()))
+            .collect();
+
+        Ok(OnboardingFacts {
+            vaults_created: vaults.iter().map(|vault| vault.created_at()).collect(),
+            active_keys,
+            encryptions,
+            // Keys are marked used after a decryption verified against its manifest
+            verified_restores: registry
+                .keys
+                .values()
+                .filter_map(|e| e.last_used())
+                .collect(),
+            recovery_kits: registry
+                .recovery_shares
+                .values()
+                .map(|config| config.created_at)
+                .collect(),
+        })
+    }
+}
+
+impl Default for OnboardingService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::vault::domain::models::OnboardingMilestone;
+    use chrono::{DateTime, TimeZone, Utc};
+
+    fn day(n: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 3, n, 9, 0, 0).unwrap()
+    }
+
+    /// Profiles of a user working through setup, one step per stage
+    fn stages() -> Vec<OnboardingFacts> {
+        let created = OnboardingFacts {
+            vaults_created: vec![day(1)],
+            active_keys: vec![("passphrase".into(), day(1))],
+            ..Default::default()
+        };
+        let two_keys = OnboardingFacts {
+            active_keys: vec![("passphrase".into(), day(1)), ("yubikey".into(), day(2))],
+            ..created.clone()
+        };
+        let encrypted = OnboardingFacts {
+            encryptions: vec![day(3)],
+            ..two_keys.clone()
+        };
+        let restored = OnboardingFacts {
+            verified_restores: vec![day(4)],
+            ..encrypted.clone()
+        };
+        let kit = OnboardingFacts {
+            recovery_kits: vec![day(5)],
+            ..restored.clone()
+        };
+        vec![
+            OnboardingFacts::default(),
+            created,
+            two_keys,
+            encrypted,
+            restored,
+            kit,
+        ]
+    }
+
+    #[test]
+    fn test_status_at_each_stage() {
+        let mut progress = OnboardingProgress::default();
+
+        for (stage, facts) in stages().iter().enumerate() {
+            let (status, newly_completed) = OnboardingService::evaluate(facts, &mut progress);
+
+            let completed: Vec<_> = status
+                .milestones
+                .iter()
+                .filter(|m| m.completed)
+                .map(|m| m.milestone)
+                .collect();
+            assert_eq!(completed, OnboardingMilestone::ALL[..stage].to_vec());
+            assert_eq!(
+                status.all_completed,
+                stage == OnboardingMilestone::ALL.len()
+            );
+
+            // Each stage completes exactly one new milestone
+            let expected_new = stage.checked_sub(1).map(|i| OnboardingMilestone::ALL[i]);
+            assert_eq!(newly_completed.first().map(|m| m.milestone), expected_new);
+            assert!(newly_completed.len() <= 1);
+        }
+
+        let (status, _) = OnboardingService::evaluate(&stages()[5], &mut progress);
+        let keys = status
+            .milestone(OnboardingMilestone::KeysOfTwoTypes)
+            .unwrap();
+        assert_eq!(keys.completed_at, Some(day(2)));
+    }
+
+    #[test]
+    fn test_milestone_stays_completed_after_key_removed() {
+        let mut progress = OnboardingProgress::default();
+        let stages = stages();
+        let two_keys = &stages[2];
+        OnboardingService::evaluate(two_keys, &mut progress);
+
+        let key_removed = OnboardingFacts {
+            active_keys: vec![("passphrase".into(), day(1))],
+            ..two_keys.clone()
+        };
+        assert!(
+            !key_removed
+                .derive()
+                .contains_key(&OnboardingMilestone::KeysOfTwoTypes)
+        );
+
+        let (status, newly_completed) = OnboardingService::evaluate(&key_removed, &mut progress);
+        let keys = status
+            .milestone(OnboardingMilestone::KeysOfTwoTypes)
+            .unwrap();
+        assert!(keys.completed);
+        assert_eq!(keys.completed_at, Some(day(2)));
+        assert!(newly_completed.is_empty());
+    }
+}
@@ -0,0 +1,63 @@
+//! Operation Log Service
+//!
+//! Appends to the hash-chained operation log and keeps each vault's manifest
+//! pointing at its newest entry. The manifest write is best effort: a vault
+//! whose manifest couldn't be updated reports a head mismatch on the next
+//! verification rather than failing the operation being recorded.
+
+use crate::prelude::*;
+use crate::services::vault::domain::{VaultError, VaultResult};
+use crate::services::vault::infrastructure::persistence::{
+    OperationLog, OperationLogEntry, OperationLogVerification, find_vault_sync, save_vault_sync,
+};
+
+/// Service for recording and verifying the operation log
+#[derive(Debug, Default)]
+pub struct OperationLogService;
+
+impl OperationLogService {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Append an entry and mirror the changed heads into the vault manifests
+    pub fn record(&self, entry: OperationLogEntry) -> VaultResult<()> {
+        let heads =
+            OperationLog::record(entry).map_err(|e| VaultError::StorageError(e.to_string()))?;
+
+        for (vault_id, head) in heads {
+            let Some(mut metadata) = find_vault_sync(&vault_id) else {
+                debug!(vault_id = %vault_id, "No manifest to mirror operation log head into");
+                continue;
+            };
+            metadata.operation_log_head = Some(head);
+            if let Err(e) = save_vault_sync(&metadata) {
+                warn!(vault_id = %vault_id, error = %e, "Failed to mirror operation log head");
+            }
+        }
+        Ok(())
+    }
+
+    /// Walk a vault's chain and compare its head with the manifest's
+    pub fn verify(&self, vault_id: &str) -> VaultResult<OperationLogVerification> {
+        let metadata =
+            find_vault_sync(vault_id).ok_or_else(|| VaultError::NotFound(vault_id.to_string()))?;
+        let log = OperationLog::load().map_err(|e| VaultError::StorageError(e.to_string()))?;
+
+        let verification = log.verify(vault_id, metadata.operation_log_head.as_deref());
+        if verification.intact {
+            info!(
+                vault_id = %vault_id,
+                entries = verification.entries_checked,
+                "Operation log chain intact"
+            );
+        } else {
+            warn!(
+                vault_id = %vault_id,
+                first_break = ?verification.first_break,
+                "Operation log chain broken"
+            );
+        }
+        Ok(verification)
+    }
+}
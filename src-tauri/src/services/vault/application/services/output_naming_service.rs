@@ -0,0 +1,274 @@
+//! Output Naming Service
+//!
+//! Names archives written to external output folders and keeps the
+//! cross-vault output index of which vault wrote what there. Before an
+//! encryption the vault's rendered name is checked against the claims of
+//! every vault in the folder; when another vault already has it, the name
+//! gets this vault's slug added or the encryption is refused, as
+//! `CrossVaultNamePolicy` says. A vault reusing its own name replaces its
+//! own archive as before.
+
+use crate::prelude::*;
+use crate::services::vault::domain::models::{
+    CrossVaultNamePolicy, shared_bundle_name, with_vault_slug,
+};
+use crate::services::vault::domain::{VaultError, VaultResult};
+use crate::services::vault::infrastructure::persistence::{OutputClaim, OutputIndex};
+use chrono::Utc;
+use std::path::{Path, PathBuf};
+
+/// Service for archive names in shared output folders
+#[derive(Debug, Default)]
+pub struct OutputNamingService {
+    /// Index location; the config directory's file when `None`
+    index_path: Option<PathBuf>,
+}
+
+impl OutputNamingService {
+    pub fn new() -> Self {
+        Self { index_path: None }
+    }
+
+    /// Service over the index at `index_path`
+    pub fn at(index_path: PathBuf) -> Self {
+        Self {
+            index_path: Some(index_path),
+        }
+    }
+
+    /// Name for a vault's next backup bundle in `output_dir`
+    ///
+    /// `rendered` is the name from the vault's template. Claims on archives
+    /// deleted outside the app are dropped first, so a freed name can be
+    /// used again.
+    pub fn resolve_name(
+        &self,
+        output_dir: &Path,
+        rendered: &str,
+        vault_id: &str,
+        vault_slug: &str,
+        policy: CrossVaultNamePolicy,
+    ) -> VaultResult<String> {
+        let dir_key = directory_key(output_dir)?;
+        let path = self.index_path()?;
+        let mut index = load(&path)?;
+        let healed = index.heal(&dir_key);
+        if healed > 0 {
+            info!(output_dir = %dir_key, healed, "Dropped claims on deleted archives");
+            if let Err(e) = index.save_to(&path) {
+                warn!(error = %e, "Failed to save healed output index");
+            }
+        }
+
+        let Some(other) = claimed_by_other(&index, &dir_key, rendered, vault_id) else {
+            return Ok(rendered.to_string());
+        };
+        let collision = |claim: &OutputClaim| VaultError::CrossVaultNameCollision {
+            archive_name: rendered.to_string(),
+            other_vault: claim.vault_name.clone(),
+        };
+        if policy == CrossVaultNamePolicy::Fail {
+            return Err(collision(other));
+        }
+
+        let name = with_vault_slug(rendered, vault_slug);
+        if let Some(other) = claimed_by_other(&index, &dir_key, &name, vault_id) {
+            return Err(collision(other));
+        }
+        info!(
+            rendered,
+            archive_name = %name,
+            other_vault = %other.vault_name,
+            "Archive name taken by another vault, added the vault slug"
+        );
+        Ok(name)
+    }
+
+    /// Record the bundles a vault wrote to `output_dir`
+    pub fn record(
+        &self,
+        output_dir: &Path,
+        file_names: &[String],
+        vault_id: &str,
+        vault_name: &str,
+    ) -> VaultResult<()> {
+        let dir_key = directory_key(output_dir)?;
+        let path = self.index_path()?;
+        let mut index = load(&path)?;
+        for file_name in file_names {
+            index.record(
+                &dir_key,
+                OutputClaim {
+                    file_name: file_name.clone(),
+                    vault_id: vault_id.to_string(),
+                    vault_name: vault_name.to_string(),
+                    recorded_at: Utc::now(),
+                },
+            );
+        }
+        index
+            .save_to(&path)
+            .map_err(|e| VaultError::StorageError(format!("Failed to save output index: {}", e)))
+    }
+
+    /// The index's claim on an archive, if its folder is a known output folder
+    pub fn claim_for(&self, archive_path: &Path) -> VaultResult<Option<OutputClaim>> {
+        let (Some(dir), Some(file_name)) = (archive_path.parent(), archive_path.file_name()) else {
+            return Ok(None);
+        };
+        let dir_key = directory_key(dir)?;
+        let index = load(&self.index_path()?)?;
+        Ok(index.claim(&dir_key, &file_name.to_string_lossy()).cloned())
+    }
+
+    fn index_path(&self) -> VaultResult<PathBuf> {
+        match &self.index_path {
+            Some(path) => Ok(path.clone()),
+            None => OutputIndex::get_index_path().map_err(|e| {
+                VaultError::StorageError(format!("Failed to locate output index: {}", e))
+            }),
+        }
+    }
+}
+
+/// Another vault's claim on `name` or the shared bundle written beside it
+fn claimed_by_other<'a>(
+    index: &'a OutputIndex,
+    dir_key: &str,
+    name: &str,
+    vault_id: &str,
+) -> Option<&'a OutputClaim> {
+    [name.to_string(), shared_bundle_name(name)]
+        .iter()
+        .filter_map(|name| index.claim(dir_key, name))
+        .find(|claim| claim.vault_id != vault_id)
+}
+
+fn directory_key(dir: &Path) -> VaultResult<String> {
+    OutputIndex::directory_key(dir)
+        .map_err(|e| VaultError::StorageError(format!("Output folder {}: {}", dir.display(), e)))
+}
+
+fn load(path: &Path) -> VaultResult<OutputIndex> {
+    OutputIndex::load_from(path)
+        .map_err(|e| VaultError::StorageError(format!("Failed to read output index: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::vault::domain::models::render_archive_name;
+    use chrono::NaiveDate;
+    use tempfile::TempDir;
+
+    /// Two vaults with `{date}.age` templates writing to one folder
+    struct Fixture {
+        _config: TempDir,
+        output: TempDir,
+        service: OutputNamingService,
+        rendered: String,
+    }
+
+    impl Fixture {
+        fn new() -> Self {
+            let config = TempDir::new().unwrap();
+            let service = OutputNamingService::at(config.path().join("output_index.json"));
+            let date = NaiveDate::from_ymd_opt(2026, 3, 1).unwrap();
+            Self {
+                _config: config,
+                output: TempDir::new().unwrap(),
+                service,
+                rendered: render_archive_name("{date}.age", "ignored", date),
+            }
+        }
+
+        /// Resolve and write a vault's archive, as an encryption would
+        fn encrypt(
+            &self,
+            vault_id: &str,
+            vault_name: &str,
+            policy: CrossVaultNamePolicy,
+        ) -> VaultResult<String> {
+            let name = self.service.resolve_name(
+                self.output.path(),
+                &self.rendered,
+                vault_id,
+                vault_name,
+                policy,
+            )?;
+            std::fs::write(self.output.path().join(&name), vault_id).unwrap();
+            self.service
+                .record(self.output.path(), &[name.clone()], vault_id, vault_name)?;
+            Ok(name)
+        }
+    }
+
+    #[test]
+    fn test_second_vault_gets_its_slug_added() {
+        let fixture = Fixture::new();
+        let policy = CrossVaultNamePolicy::Disambiguate;
+
+        let first = fixture.encrypt("vault-a", "Family", policy).unwrap();
+        let second = fixture.encrypt("vault-b", "Business", policy).unwrap();
+        assert_eq!(first, "2026-03-01.age");
+        assert_eq!(second, "2026-03-01-Business.age");
+
+        // Each vault keeps replacing its own archive
+        assert_eq!(fixture.encrypt("vault-a", "Family", policy).unwrap(), first);
+        assert_eq!(
+            fixture.encrypt("vault-b", "Business", policy).unwrap(),
+            second
+        );
+
+        let claim = fixture
+            .service
+            .claim_for(&fixture.output.path().join(&second))
+            .unwrap()
+            .unwrap();
+        assert_eq!(claim.vault_name, "Business");
+    }
+
+    #[test]
+    fn test_fail_policy_reports_the_other_vault() {
+        let fixture = Fixture::new();
+        let policy = CrossVaultNamePolicy::Fail;
+
+        fixture.encrypt("vault-a", "Family", policy).unwrap();
+        match fixture.encrypt("vault-b", "Business", policy) {
+            Err(VaultError::CrossVaultNameCollision {
+                archive_name,
+                other_vault,
+            }) => {
+                assert_eq!(archive_name, "2026-03-01.age");
+                assert_eq!(other_vault, "Family");
+            }
+            other => panic!("expected a collision, got {other:?}"),
+        }
+        assert!(
+            !fixture
+                .output
+                .path()
+                .join("2026-03-01-Business.age")
+                .exists()
+        );
+    }
+
+    #[test]
+    fn test_claims_on_deleted_archives_are_healed() {
+        let fixture = Fixture::new();
+        let policy = CrossVaultNamePolicy::Fail;
+
+        let first = fixture.encrypt("vault-a", "Family", policy).unwrap();
+        std::fs::remove_file(fixture.output.path().join(&first)).unwrap();
+
+        // The name is free again once the archive is gone
+        let second = fixture.encrypt("vault-b", "Business", policy).unwrap();
+        assert_eq!(second, first);
+        let claim = fixture
+            .service
+            .claim_for(&fixture.output.path().join(&second))
+            .unwrap()
+            .unwrap();
+        assert_eq!(claim.vault_id, "vault-b");
+    }
+}
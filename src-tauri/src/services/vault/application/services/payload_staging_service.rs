@@ -5,7 +5,8 @@
 
 use crate::prelude::*;
 use crate::services::file::infrastructure::file_operations::{
-    self as file_ops, ArchiveOperation, FileOpsConfig, FileSelection,
+    self as file_ops, ArchiveOperation, FileOpsConfig, FileSelection, ResilientSource,
+    TransformedFile,
 };
 use crate::services::shared::infrastructure::get_keys_dir;
 use crate::services::vault::application::services::RecoveryTxtService;
@@ -13,7 +14,8 @@
 use crate::services::vault::infrastructure::persistence::metadata::{
     BundleType, RecipientType, VaultMetadata,
 };
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
 type Result<T> = std::result::Result<T, VaultError>;
 
@@ -46,6 +48,37 @@ pub fn create_vault_payload(
         vault_metadata: &VaultMetadata,
         output_path: &Path,
         bundle_type: BundleType,
+    ) -> Result<ArchiveOperation> {
+        self.create_vault_payload_excluding(
+            user_file_selection,
+            vault_metadata,
+            output_path,
+            bundle_type,
+            &[],
+            None,
+            None,
+            &[],
+        )
+    }
+
+    /// Create a vault payload, leaving out the given source files
+    ///
+    /// Used when unreadable files were skipped while building the manifest,
+    /// so the archive matches the manifest's `skipped_entries`. With a
+    /// `resilient_source`, user files are read with retries (slow media).
+    /// `compression_level` overrides the default gzip level. `transformed`
+    /// files are archived in place of their sources.
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_vault_payload_excluding(
+        &self,
+        user_file_selection: &FileSelection,
+        vault_metadata: &VaultMetadata,
+        output_path: &Path,
+        bundle_type: BundleType,
+        excluded: &[PathBuf],
+        resilient_source: Option<&Arc<ResilientSource>>,
+        compression_level: Option<u32>,
+        transformed: &[TransformedFile],
     ) -> Result<ArchiveOperation> {
         let is_shared = matches!(bundle_type, BundleType::Shared);
 
@@ -61,15 +94,10 @@ pub fn create_vault_payload(
             VaultError::OperationFailed(format!("Failed to create staging area: {}", e))
         })?;
 
-        // Step 1: Stage user files
-        staging.stage_files(user_file_selection).map_err(|e| {
-            VaultError::OperationFailed(format!("Failed to stage user files: {}", e))
-        })?;
-
-        info!(file_count = staging.file_count(), "Staged user files");
-
-        // Step 2: Add manifest to staging (ONLY for backup bundles)
-        // Shared bundles contain ONLY user files - no manifest, no .agekey.enc
+        // Step 1: Add manifest to staging (ONLY for backup bundles)
+        // Staged first so it is the first TAR entry and can be read without
+        // unpacking the whole archive. Shared bundles contain ONLY user files -
+        // no manifest, no .agekey.enc
         if !is_shared {
             let manifest_json = serde_json::to_string_pretty(&vault_metadata).map_err(|e| {
                 VaultError::OperationFailed(format!("Failed to serialize manifest: {}", e))
@@ -87,6 +115,24 @@ pub fn create_vault_payload(
             info!("Skipping manifest for shared bundle (files only)");
         }
 
+        // Step 2: Stage user files
+        staging.exclude_paths(excluded.iter().cloned());
+        if let Some(source) = resilient_source {
+            staging.use_resilient_source(Arc::clone(source));
+        }
+        for file in transformed {
+            staging.substitute(
+                file.source_path.clone(),
+                file.staged_path.clone(),
+                file.file_name().to_os_string(),
+            );
+        }
+        staging.stage_files(user_file_selection).map_err(|e| {
+            VaultError::OperationFailed(format!("Failed to stage user files: {}", e))
+        })?;
+
+        info!(file_count = staging.file_count(), "Staged user files");
+
         // Step 3: Add passphrase .agekey.enc files to staging
         // ONLY for backup bundles - shared bundles exclude private key material
         let enc_files_added = if is_shared {
@@ -105,7 +151,10 @@ pub fn create_vault_payload(
         // It will be written separately alongside the .age file
 
         // Step 5: Create TAR from complete staging
-        let config = FileOpsConfig::default();
+        let mut config = FileOpsConfig::default();
+        if let Some(level) = compression_level {
+            config.compression_level = level;
+        }
         let archive_info =
             file_ops::archive_operations::creation::create_tar_gz(&staging, output_path, &config)
                 .map_err(|e| VaultError::OperationFailed(format!("Failed to create TAR: {}", e)))?;
@@ -132,12 +181,13 @@ pub fn create_vault_payload(
     /// Write RECOVERY.txt file alongside encrypted vault
     ///
     /// Creates plaintext recovery instructions in same folder as .age file
-    /// This is written OUTSIDE the encrypted bundle for accessibility
+    /// This is written OUTSIDE the encrypted bundle for accessibility.
+    /// Returns the path written.
     pub fn write_recovery_file(
         &self,
         vault_metadata: &VaultMetadata,
         age_file_path: &Path,
-    ) -> Result<()> {
+    ) -> Result<PathBuf> {
         // Generate recovery content with updated format
         let recovery_txt = self.recovery_service.generate(vault_metadata);
 
@@ -159,7 +209,7 @@ pub fn write_recovery_file(
             "Created recovery instructions file"
         );
 
-        Ok(())
+        Ok(recovery_path)
     }
 
     /// Add passphrase encryption key files to staging
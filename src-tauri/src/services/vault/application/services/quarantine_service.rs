@@ -0,0 +1,575 @@
+//! Quarantine Service
+//!
+//! Finds archive files left incomplete by an interrupted encryption and moves
+//! them to `quarantine/<vault>/` under the vaults directory, each with a
+//! `.quarantine.json` record of why. Index entries for quarantined archives
+//! are removed so the archive list and file search stop offering them.
+//!
+//! Files in quarantine are only deleted by `purge`, or by storage quota
+//! cleanup once they've been there for `StorageQuotaService`'s minimum age.
+//! Archives marked immutable are reported but left in place.
+
+use crate::prelude::*;
+use crate::services::crypto::infrastructure::archive_reader;
+use crate::services::shared::infrastructure::io::atomic_write_sync;
+use crate::services::shared::infrastructure::path_management::SensitiveDirectory;
+use crate::services::shared::infrastructure::{
+    ClockService, SecureDeleteService, get_vaults_directory,
+};
+use crate::services::vault::application::services::FileSearchService;
+use crate::services::vault::domain::models::{
+    IncompleteArchiveReport, IncompleteArtifact, IncompleteArtifactKind, QuarantinePurgeReport,
+    QuarantineRecord,
+};
+use crate::services::vault::domain::{VaultError, VaultResult};
+use crate::services::vault::infrastructure::persistence::{ArchiveIndex, VaultMetadata};
+use crate::services::vault::infrastructure::vault_repository::storage_error;
+use crate::types::ByteSize;
+use std::fs;
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+
+/// Folder under the vaults directory holding one quarantine per vault
+pub const QUARANTINE_DIR: &str = "quarantine";
+
+/// Suffix of the record written beside each quarantined file
+const RECORD_SUFFIX: &str = ".quarantine.json";
+
+/// Service for detecting, quarantining, and purging incomplete archives
+#[derive(Debug)]
+pub struct QuarantineService {
+    clock: ClockService,
+    file_search: FileSearchService,
+    deleter: SecureDeleteService,
+}
+
+impl QuarantineService {
+    pub fn new() -> Self {
+        Self::with_clock(ClockService::global().clone())
+    }
+
+    pub fn with_clock(clock: ClockService) -> Self {
+        Self {
+            clock,
+            file_search: FileSearchService::new(),
+            deleter: SecureDeleteService::new(),
+        }
+    }
+
+    /// Quarantine a vault's incomplete archives and report what was found
+    pub fn scan_vault(&self, vault: &VaultMetadata) -> VaultResult<IncompleteArchiveReport> {
+        let mut index =
+            ArchiveIndex::load().map_err(|e| VaultError::StorageError(e.to_string()))?;
+        let report = self.quarantine_in(&vaults_dir()?, vault, &mut index)?;
+
+        if !report.removed_index_entries.is_empty() {
+            index.save().map_err(storage_error)?;
+            self.file_search
+                .remove_archives(&report.vault_id, &report.removed_index_entries);
+        }
+        if !report.artifacts.is_empty() {
+            warn!(
+                vault_id = %report.vault_id,
+                artifacts = report.artifacts.len(),
+                "Quarantined incomplete archives"
+            );
+        }
+        Ok(report)
+    }
+
+    /// Delete a vault's quarantined files older than `older_than_days`
+    ///
+    /// Refused while the clock is unreliable, since file ages can't be told.
+    pub fn purge(
+        &self,
+        vault: &VaultMetadata,
+        older_than_days: u32,
+    ) -> VaultResult<QuarantinePurgeReport> {
+        let dir = quarantine_dir(&vaults_dir()?, &vault.vault.sanitized_name);
+        let report = self.purge_in(&dir, vault.vault_id(), older_than_days)?;
+        info!(
+            vault_id = %report.vault_id,
+            purged = report.purged.len(),
+            remaining = report.remaining,
+            "Purged quarantine"
+        );
+        Ok(report)
+    }
+
+    fn quarantine_in(
+        &self,
+        vaults_dir: &Path,
+        vault: &VaultMetadata,
+        index: &mut ArchiveIndex,
+    ) -> VaultResult<IncompleteArchiveReport> {
+        let sanitized_name = &vault.vault.sanitized_name;
+        let target_dir = quarantine_dir(vaults_dir, sanitized_name);
+        let mut report = IncompleteArchiveReport {
+            vault_id: vault.vault_id().to_string(),
+            artifacts: Vec::new(),
+            removed_index_entries: Vec::new(),
+            quarantine_dir: target_dir.display().to_string(),
+        };
+
+        for (path, archive_name, kind, size) in find_incomplete(vaults_dir, sanitized_name)? {
+            let file_name = path
+                .file_name()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .to_string();
+            let is_archive = matches!(
+                kind,
+                IncompleteArtifactKind::EmptyArchive | IncompleteArtifactKind::InvalidHeader
+            );
+            let entry = is_archive
+                .then(|| index.current_entry(&archive_name).cloned())
+                .flatten();
+
+            let mut artifact = IncompleteArtifact {
+                file_name,
+                kind,
+                size: ByteSize(size),
+                quarantined_as: None,
+                skipped_reason: None,
+            };
+            if entry.as_ref().is_some_and(|entry| entry.immutable) {
+                artifact.skipped_reason = Some("Archive is marked immutable".to_string());
+                report.artifacts.push(artifact);
+                continue;
+            }
+
+            let moved_to = self.move_to_quarantine(&path, &target_dir, kind, size)?;
+            artifact.quarantined_as = Some(moved_to.display().to_string());
+            if let Some(entry) = entry
+                && index.remove(&entry.vault_id, &entry.archive_id).is_some()
+            {
+                report.removed_index_entries.push(entry.archive_id);
+            }
+            report.artifacts.push(artifact);
+        }
+
+        Ok(report)
+    }
+
+    fn move_to_quarantine(
+        &self,
+        path: &Path,
+        target_dir: &Path,
+        kind: IncompleteArtifactKind,
+        size: u64,
+    ) -> VaultResult<PathBuf> {
+        SensitiveDirectory::create(target_dir)
+            .map_err(|e| VaultError::StorageError(e.to_string()))?;
+
+        let now = self.clock.now();
+        let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+        let destination = target_dir.join(format!(
+            "{}-{}",
+            now.format("%Y%m%dT%H%M%S%.3f"),
+            file_name.trim_start_matches('.')
+        ));
+
+        let record = QuarantineRecord {
+            original_path: path.display().to_string(),
+            kind,
+            reason: kind.reason().to_string(),
+            size,
+            quarantined_at: now,
+        };
+        let json = serde_json::to_vec_pretty(&record)
+            .map_err(|e| VaultError::StorageError(e.to_string()))?;
+
+        // Record first, so a quarantined file never sits there unexplained
+        atomic_write_sync(&record_path(&destination), &json)
+            .map_err(|e| VaultError::StorageError(e.to_string()))?;
+        fs::rename(path, &destination).map_err(|e| {
+            let _ = self.deleter.delete_file(&record_path(&destination));
+            VaultError::StorageError(format!("Failed to quarantine {}: {}", path.display(), e))
+        })?;
+
+        debug!(
+            from = %path.display(),
+            to = %destination.display(),
+            ?kind,
+            "Moved incomplete archive to quarantine"
+        );
+        Ok(destination)
+    }
+
+    fn purge_in(
+        &self,
+        dir: &Path,
+        vault_id: &str,
+        older_than_days: u32,
+    ) -> VaultResult<QuarantinePurgeReport> {
+        let mut report = QuarantinePurgeReport {
+            vault_id: vault_id.to_string(),
+            purged: Vec::new(),
+            freed: ByteSize::ZERO,
+            remaining: 0,
+            delete_honesty: None,
+        };
+        if !dir.exists() {
+            return Ok(report);
+        }
+        if !self.clock.is_reliable() {
+            return Err(VaultError::InvalidOperation(
+                "The system clock is unreliable, so the age of quarantined files can't be \
+                 determined. Fix the clock before purging"
+                    .to_string(),
+            ));
+        }
+
+        for path in quarantined_files(dir)? {
+            let record = read_record(&path);
+            // Files without a readable record are never purged
+            let old_enough = record.as_ref().is_some_and(|record| {
+                self.clock
+                    .days_since(record.quarantined_at)
+                    .is_some_and(|days| days >= i64::from(older_than_days))
+            });
+            if !old_enough {
+                report.remaining += 1;
+                continue;
+            }
+
+            let size = fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+            let honesty = self
+                .deleter
+                .delete_file(&path)
+                .map_err(|e| VaultError::StorageError(e.to_string()))?;
+            let _ = self.deleter.delete_file(&record_path(&path));
+            let weakest = report.delete_honesty.map_or(honesty, |h| h.min(honesty));
+            report.delete_honesty = Some(weakest);
+            report.freed = ByteSize(report.freed.bytes() + size);
+            report.purged.push(
+                path.file_name()
+                    .unwrap_or_default()
+                    .to_string_lossy()
+                    .to_string(),
+            );
+        }
+
+        Ok(report)
+    }
+}
+
+impl Default for QuarantineService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A vault's incomplete archive files: path, archive name, kind, and size
+fn find_incomplete(
+    vaults_dir: &Path,
+    sanitized_name: &str,
+) -> VaultResult<Vec<(PathBuf, String, IncompleteArtifactKind, u64)>> {
+    if !vaults_dir.exists() {
+        return Ok(Vec::new());
+    }
+    let archive_names = [
+        format!("{}.age", sanitized_name),
+        format!("{}-shared.age", sanitized_name),
+    ];
+
+    let entries = fs::read_dir(vaults_dir).map_err(|e| VaultError::StorageError(e.to_string()))?;
+    let mut found = Vec::new();
+    for entry in entries.filter_map(|e| e.ok()) {
+        let path = entry.path();
+        let Ok(metadata) = entry.metadata() else {
+            continue;
+        };
+        if !metadata.is_file() {
+            continue;
+        }
+        let file_name = entry.file_name().to_string_lossy().to_string();
+        for archive_name in &archive_names {
+            if let Some(kind) = classify(&path, &file_name, archive_name, metadata.len()) {
+                found.push((path.clone(), archive_name.clone(), kind, metadata.len()));
+                break;
+            }
+        }
+    }
+    found.sort_by(|a, b| a.0.cmp(&b.0));
+    Ok(found)
+}
+
+/// Classify `file_name` as an incomplete form of `archive_name`, if it is one
+fn classify(
+    path: &Path,
+    file_name: &str,
+    archive_name: &str,
+    size: u64,
+) -> Option<IncompleteArtifactKind> {
+    if file_name == archive_name {
+        if size == 0 {
+            return Some(IncompleteArtifactKind::EmptyArchive);
+        }
+        let file = fs::File::open(path).ok()?;
+        let valid = archive_reader(BufReader::new(file))
+            .is_ok_and(|reader| age::Decryptor::new(reader).is_ok());
+        return (!valid).then_some(IncompleteArtifactKind::InvalidHeader);
+    }
+    if file_name == format!("{}.tmp", archive_name) {
+        return Some(IncompleteArtifactKind::TempFile);
+    }
+
+    // Safe overwrite siblings: `.<archive>.<uuid>.partial` / `.previous`
+    let rest = file_name.strip_prefix(&format!(".{}.", archive_name))?;
+    let (id, suffix) = rest.split_once('.')?;
+    if id.len() != 32 || !id.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    match suffix {
+        "partial" => Some(IncompleteArtifactKind::StagedPartial),
+        "previous" => Some(IncompleteArtifactKind::LeftoverPrevious),
+        _ => None,
+    }
+}
+
+/// Quarantined files in `dir`, excluding their records
+pub(crate) fn quarantined_files(dir: &Path) -> VaultResult<Vec<PathBuf>> {
+    let entries = fs::read_dir(dir).map_err(|e| VaultError::StorageError(e.to_string()))?;
+    let mut files: Vec<PathBuf> = entries
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|path| path.is_file() && !path.to_string_lossy().ends_with(RECORD_SUFFIX))
+        .filter(|path| !SensitiveDirectory::is_marker(path.file_name().unwrap_or_default()))
+        .collect();
+    files.sort();
+    Ok(files)
+}
+
+fn quarantine_dir(vaults_dir: &Path, sanitized_name: &str) -> PathBuf {
+    vaults_dir.join(QUARANTINE_DIR).join(sanitized_name)
+}
+
+pub(crate) fn record_path(quarantined: &Path) -> PathBuf {
+    let mut name = quarantined.file_name().unwrap_or_default().to_os_string();
+    name.push(RECORD_SUFFIX);
+    quarantined.with_file_name(name)
+}
+
+/// Record written when `quarantined` was moved in, if it's readable
+pub(crate) fn read_record(quarantined: &Path) -> Option<QuarantineRecord> {
+    let json = fs::read_to_string(record_path(quarantined)).ok()?;
+    serde_json::from_str(&json).ok()
+}
+
+fn vaults_dir() -> VaultResult<PathBuf> {
+    get_vaults_directory().map_err(|e| VaultError::StorageError(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::shared::infrastructure::DeviceInfo;
+    use crate::services::shared::infrastructure::clock::testing::{FakeClock, service};
+    use crate::services::vault::domain::models::ArchiveIndexEntry;
+    use std::time::Duration;
+    use tempfile::TempDir;
+
+    const STAGING_ID: &str = "0123456789abcdef0123456789abcdef";
+
+    fn manifest(name: &str) -> VaultMetadata {
+        let device_info = DeviceInfo {
+            machine_id: "machine-1".to_string(),
+            machine_label: "laptop".to_string(),
+            created_at: chrono::Utc::now(),
+            app_version: "2.0.0".to_string(),
+        };
+        VaultMetadata::new(
+            format!("vault-{}", name),
+            name.to_string(),
+            None,
+            name.to_string(),
+            &device_info,
+            None,
+            vec![],
+            vec![],
+            0,
+            0,
+        )
+    }
+
+    fn index_entry(
+        vault: &VaultMetadata,
+        archive_name: &str,
+        immutable: bool,
+    ) -> ArchiveIndexEntry {
+        ArchiveIndexEntry {
+            archive_id: format!("archive-{}", archive_name),
+            vault_id: vault.vault_id().to_string(),
+            archive_name: archive_name.to_string(),
+            encryption_revision: 1,
+            created_at: chrono::Utc::now(),
+            file_count: 1,
+            comment: None,
+            comment_updated_at: None,
+            immutable,
+            parity: None,
+            migrated_from: None,
+            prune_marked: false,
+            remapped_from: None,
+            archive_sha256: None,
+            recipients: Vec::new(),
+        }
+    }
+
+    fn valid_archive() -> Vec<u8> {
+        let recipient = age::x25519::Identity::generate().to_public();
+        age::encrypt(&recipient, b"fixture payload").unwrap()
+    }
+
+    #[test]
+    fn test_classifies_each_artifact_type() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir = temp_dir.path();
+        let write = |name: &str, bytes: &[u8]| fs::write(dir.join(name), bytes).unwrap();
+        write("Family.age", b"");
+        write("Family-shared.age", b"not an age file");
+        write("Family.age.tmp", b"half");
+        write(&format!(".Family.age.{}.partial", STAGING_ID), b"staged");
+        write(
+            &format!(".Family-shared.age.{}.previous", STAGING_ID),
+            b"old",
+        );
+        // Complete archives and other vaults' files are left alone
+        write("Other.age", b"");
+        write("Family-Docs.age", &valid_archive());
+        write(".Family.age.not-a-uuid.partial", b"?");
+
+        let found: Vec<(String, IncompleteArtifactKind)> = find_incomplete(dir, "Family")
+            .unwrap()
+            .into_iter()
+            .map(|(path, _, kind, _)| {
+                (
+                    path.file_name().unwrap().to_string_lossy().to_string(),
+                    kind,
+                )
+            })
+            .collect();
+
+        assert_eq!(found.len(), 5);
+        let kind_of = |name: &str| found.iter().find(|(n, _)| n == name).map(|(_, k)| *k);
+        assert_eq!(
+            kind_of("Family.age"),
+            Some(IncompleteArtifactKind::EmptyArchive)
+        );
+        assert_eq!(
+            kind_of("Family-shared.age"),
+            Some(IncompleteArtifactKind::InvalidHeader)
+        );
+        assert_eq!(
+            kind_of("Family.age.tmp"),
+            Some(IncompleteArtifactKind::TempFile)
+        );
+        assert_eq!(
+            kind_of(&format!(".Family.age.{}.partial", STAGING_ID)),
+            Some(IncompleteArtifactKind::StagedPartial)
+        );
+        assert_eq!(
+            kind_of(&format!(".Family-shared.age.{}.previous", STAGING_ID)),
+            Some(IncompleteArtifactKind::LeftoverPrevious)
+        );
+
+        fs::write(dir.join("Family.age"), valid_archive()).unwrap();
+        assert_eq!(find_incomplete(dir, "Family").unwrap().len(), 4);
+    }
+
+    #[test]
+    fn test_quarantine_moves_files_and_drops_index_entries() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir = temp_dir.path();
+        let vault = manifest("Family");
+        fs::write(dir.join("Family.age"), b"").unwrap();
+        fs::write(
+            dir.join(format!(".Family.age.{}.partial", STAGING_ID)),
+            b"x",
+        )
+        .unwrap();
+
+        let mut index = ArchiveIndex::default();
+        index.record(index_entry(&vault, "Family.age", false));
+
+        let clock = FakeClock::at("2026-03-01T00:00:00Z");
+        let service = QuarantineService::with_clock(service(&clock));
+        let report = service.quarantine_in(dir, &vault, &mut index).unwrap();
+
+        assert_eq!(report.artifacts.len(), 2);
+        assert_eq!(report.removed_index_entries, ["archive-Family.age"]);
+        assert!(index.entries(vault.vault_id()).is_empty());
+        assert!(!dir.join("Family.age").exists());
+
+        for artifact in &report.artifacts {
+            let moved = PathBuf::from(artifact.quarantined_as.as_ref().unwrap());
+            assert!(moved.starts_with(dir.join(QUARANTINE_DIR).join("Family")));
+            let record: QuarantineRecord =
+                serde_json::from_str(&fs::read_to_string(record_path(&moved)).unwrap()).unwrap();
+            assert_eq!(record.kind, artifact.kind);
+            assert_eq!(record.reason, artifact.kind.reason());
+        }
+
+        // A second scan finds nothing new
+        let again = service.quarantine_in(dir, &vault, &mut index).unwrap();
+        assert!(again.artifacts.is_empty());
+    }
+
+    #[test]
+    fn test_quarantine_leaves_immutable_archives_in_place() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir = temp_dir.path();
+        let vault = manifest("Family");
+        fs::write(dir.join("Family.age"), b"corrupt").unwrap();
+
+        let mut index = ArchiveIndex::default();
+        index.record(index_entry(&vault, "Family.age", true));
+
+        let clock = FakeClock::at("2026-03-01T00:00:00Z");
+        let service = QuarantineService::with_clock(service(&clock));
+        let report = service.quarantine_in(dir, &vault, &mut index).unwrap();
+
+        assert!(report.artifacts[0].quarantined_as.is_none());
+        assert!(report.artifacts[0].skipped_reason.is_some());
+        assert!(report.removed_index_entries.is_empty());
+        assert!(dir.join("Family.age").exists());
+    }
+
+    #[test]
+    fn test_purge_only_removes_old_entries() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir = temp_dir.path();
+        let vault = manifest("Family");
+        let clock = FakeClock::at("2026-03-01T00:00:00Z");
+        let service = QuarantineService::with_clock(service(&clock));
+        let quarantine = quarantine_dir(dir, "Family");
+
+        fs::write(dir.join("Family.age.tmp"), b"old").unwrap();
+        service
+            .quarantine_in(dir, &vault, &mut ArchiveIndex::default())
+            .unwrap();
+        clock.advance(Duration::from_secs(10 * 86_400));
+        fs::write(dir.join("Family-shared.age.tmp"), b"newer").unwrap();
+        service
+            .quarantine_in(dir, &vault, &mut ArchiveIndex::default())
+            .unwrap();
+        // No record: never purged
+        fs::write(quarantine.join("unexplained.bin"), b"?").unwrap();
+
+        let report = service.purge_in(&quarantine, vault.vault_id(), 7).unwrap();
+        assert_eq!(report.purged.len(), 1);
+        assert!(report.purged[0].ends_with("Family.age.tmp"));
+        assert_eq!(report.freed, ByteSize(3));
+        assert_eq!(report.remaining, 2);
+        assert_eq!(
+            report.delete_honesty,
+            Some(SecureDeleteService::new().capability(&quarantine).honesty)
+        );
+        assert_eq!(quarantined_files(&quarantine).unwrap().len(), 2);
+
+        clock.set_wall("1970-01-01T00:00:00Z");
+        let refused = service.purge_in(&quarantine, vault.vault_id(), 0);
+        assert!(matches!(refused, Err(VaultError::InvalidOperation(_))));
+    }
+}
@@ -2,6 +2,7 @@
 //!
 //! Generates human-readable recovery instructions for encrypted vault bundles.
 
+use crate::services::shared::infrastructure::formatting::{Locale, format_bytes, format_date};
 use crate::services::vault::infrastructure::persistence::metadata::{RecipientType, VaultMetadata};
 
 /// Service for generating RECOVERY.txt files
@@ -26,7 +27,7 @@ pub fn generate(&self, metadata: &VaultMetadata) -> String {
         content.push_str(&format!("Vault Name: {}\n", metadata.label()));
         content.push_str(&format!(
             "Created: {}\n",
-            metadata.created_at().format("%B %d, %Y")
+            format_date(metadata.created_at(), Locale::En)
         ));
         content.push_str(&format!(
             "Encrypted File: {}.age\n\n",
@@ -104,36 +105,12 @@ pub fn generate(&self, metadata: &VaultMetadata) -> String {
             "VAULT CONTENTS: {} file{}, {} total\n",
             metadata.file_count(),
             if metadata.file_count() == 1 { "" } else { "s" },
-            Self::format_size(metadata.total_size())
+            format_bytes(metadata.total_size(), Locale::En)
         ));
         content.push_str("───────────────────────────────────────────────\n");
 
         content
     }
-
-    /// Format file size in human-readable format
-    fn format_size(bytes: u64) -> String {
-        const UNITS: &[&str] = &["B", "KB", "MB", "GB"];
-        const THRESHOLD: f64 = 1024.0;
-
-        if bytes == 0 {
-            return "0 B".to_string();
-        }
-
-        let mut size = bytes as f64;
-        let mut unit_index = 0;
-
-        while size >= THRESHOLD && unit_index < UNITS.len() - 1 {
-            size /= THRESHOLD;
-            unit_index += 1;
-        }
-
-        if unit_index == 0 {
-            format!("{} {}", bytes, UNITS[unit_index])
-        } else {
-            format!("{:.1} {}", size, UNITS[unit_index])
-        }
-    }
 }
 
 impl Default for RecoveryTxtService {
@@ -146,6 +123,7 @@ fn default() -> Self {
 mod tests {
     use super::*;
 
+    use crate::services::file::infrastructure::file_operations::{HashAlgorithm, RawPath};
     use crate::services::shared::infrastructure::DeviceInfo;
     use crate::services::vault::infrastructure::persistence::metadata::{
         RecipientInfo, VaultFileEntry,
@@ -178,13 +156,29 @@ fn test_generate_recovery_txt_passphrase_only() {
             vec![
                 VaultFileEntry {
                     path: "document.pdf".to_string(),
+                    raw_path: RawPath::from_display("document.pdf"),
+                    lossy_name: false,
                     size: 1024,
                     sha256: "abc123".to_string(),
+                    hash_algorithm: HashAlgorithm::Sha256,
+                    digest: None,
+                    ownership: None,
+                    content_type: None,
+                    content_type_mismatch: false,
+                    transform: None,
                 },
                 VaultFileEntry {
                     path: "photo.jpg".to_string(),
+                    raw_path: RawPath::from_display("photo.jpg"),
+                    lossy_name: false,
                     size: 2048,
                     sha256: "def456".to_string(),
+                    hash_algorithm: HashAlgorithm::Sha256,
+                    digest: None,
+                    ownership: None,
+                    content_type: None,
+                    content_type_mismatch: false,
+                    transform: None,
                 },
             ],
             2,
@@ -199,7 +193,7 @@ fn test_generate_recovery_txt_passphrase_only() {
         assert!(recovery_txt.contains("Passphrase Key"));
         assert!(recovery_txt.contains("my-backup-key.agekey.enc"));
         assert!(recovery_txt.contains("2 files"));
-        assert!(recovery_txt.contains("3.0 KB")); // Total size
+        assert!(recovery_txt.contains("3.1 kB")); // Total size
         assert!(!recovery_txt.contains("document.pdf")); // Filenames should NOT be present
         assert!(!recovery_txt.contains("photo.jpg")); // Filenames should NOT be present
         assert!(!recovery_txt.contains("Location: Check")); // Location hint should NOT be present
@@ -249,16 +243,6 @@ fn test_generate_recovery_txt_yubikey() {
         assert!(recovery_txt.contains("https://barqly.com/recovery"));
     }
 
-    #[test]
-    fn test_format_size() {
-        assert_eq!(RecoveryTxtService::format_size(0), "0 B");
-        assert_eq!(RecoveryTxtService::format_size(512), "512 B");
-        assert_eq!(RecoveryTxtService::format_size(1024), "1.0 KB");
-        assert_eq!(RecoveryTxtService::format_size(1536), "1.5 KB");
-        assert_eq!(RecoveryTxtService::format_size(1048576), "1.0 MB");
-        assert_eq!(RecoveryTxtService::format_size(10485760), "10.0 MB");
-    }
-
     #[test]
     fn test_generate_hybrid_mode() {
         let device_info = DeviceInfo {
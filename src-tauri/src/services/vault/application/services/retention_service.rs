@@ -0,0 +1,242 @@
+//! Archive Retention Service
+//!
+//! Stores each vault's retention policy in the local vault settings and
+//! applies it to the vault's archive index. Evaluating only classifies
+//! archives; they're removed solely by `prune_eligible`, which goes through
+//! `ArchiveService::prune_archives` and its typed confirmation.
+//!
+//! Calendar windows are measured from the clock, so while it's unreliable
+//! the policy isn't applied. Only archives a migration marked for pruning
+//! are eligible then, as they are for vaults without a policy.
+
+use crate::prelude::*;
+use crate::services::shared::infrastructure::ClockService;
+use crate::services::vault::application::services::ArchiveService;
+use crate::services::vault::domain::models::{
+    ArchiveIndexEntry, ArchivePruneReport, RetentionEvaluation, RetentionPolicy, RetentionSummary,
+    evaluate_retention, unevaluated_retention,
+};
+use crate::services::vault::domain::{VaultError, VaultResult};
+use crate::services::vault::infrastructure::persistence::VaultSettingsRegistry;
+
+/// Service for per-vault archive retention
+#[derive(Debug)]
+pub struct RetentionService {
+    clock: ClockService,
+    archive_service: ArchiveService,
+}
+
+impl RetentionService {
+    pub fn new() -> Self {
+        Self::with_clock(ClockService::global().clone())
+    }
+
+    pub fn with_clock(clock: ClockService) -> Self {
+        Self {
+            clock,
+            archive_service: ArchiveService::new(),
+        }
+    }
+
+    /// A vault's retention policy (none if never set)
+    pub fn get_policy(&self, vault_id: &str) -> VaultResult<Option<RetentionPolicy>> {
+        Ok(load_settings()?.get(vault_id).retention_policy)
+    }
+
+    /// Replace a vault's retention policy, or clear it with `None`
+    pub fn set_policy(
+        &self,
+        vault_id: &str,
+        policy: Option<RetentionPolicy>,
+    ) -> VaultResult<Option<RetentionPolicy>> {
+        if let Some(policy) = &policy {
+            policy.validate().map_err(VaultError::InvalidOperation)?;
+        }
+
+        let mut settings = load_settings()?;
+        settings.entry(vault_id).retention_policy = policy.clone();
+        save_settings(&settings)?;
+
+        info!(
+            vault_id,
+            cleared = policy.is_none(),
+            "Updated retention policy"
+        );
+        Ok(policy)
+    }
+
+    /// Classify a vault's archives under its policy
+    pub fn evaluate(&self, vault_id: &str) -> VaultResult<RetentionEvaluation> {
+        let policy = self.get_policy(vault_id)?;
+        let entries = self.archive_service.list_archives(vault_id)?;
+
+        let mut evaluation = self.classify(vault_id, policy, &entries);
+        evaluation.summary.reclaimable = self
+            .archive_service
+            .prune_reclaimable(vault_id, &evaluation.eligible_ids())?;
+
+        debug!(
+            vault_id,
+            eligible = evaluation.summary.eligible_count,
+            reclaimable = evaluation.summary.reclaimable.bytes(),
+            "Evaluated retention policy"
+        );
+        Ok(evaluation)
+    }
+
+    /// Eligible count and reclaimable space for a vault
+    pub fn summary(&self, vault_id: &str) -> VaultResult<RetentionSummary> {
+        Ok(self.evaluate(vault_id)?.summary)
+    }
+
+    /// Prune exactly the archives the policy currently marks eligible
+    pub fn prune_eligible(
+        &self,
+        vault_id: &str,
+        confirmation: Option<&str>,
+    ) -> VaultResult<ArchivePruneReport> {
+        let evaluation = self.evaluate(vault_id)?;
+        // Without a policy, only archives marked by a migration are eligible
+        if evaluation.policy.is_none() && evaluation.summary.eligible_count == 0 {
+            return Err(VaultError::InvalidOperation(
+                "No retention policy is set for this vault".to_string(),
+            ));
+        }
+        if let Some(reason) = evaluation.skipped_reason {
+            return Err(VaultError::InvalidOperation(reason));
+        }
+
+        self.archive_service
+            .prune_archives(vault_id, &evaluation.eligible_ids(), confirmation)
+    }
+
+    /// Apply `policy` to `entries` at the clock's current time
+    ///
+    /// Leaves `reclaimable` at zero; it depends on the files on disk.
+    fn classify(
+        &self,
+        vault_id: &str,
+        policy: Option<RetentionPolicy>,
+        entries: &[ArchiveIndexEntry],
+    ) -> RetentionEvaluation {
+        let (archives, skipped_reason) = match &policy {
+            None => (unevaluated_retention(entries), None),
+            Some(_) if !self.clock.is_reliable() => {
+                warn!(
+                    vault_id,
+                    "System clock is unreliable; not applying retention policy"
+                );
+                (
+                    unevaluated_retention(entries),
+                    Some(
+                        "The system clock looks wrong, so archive ages can't be trusted"
+                            .to_string(),
+                    ),
+                )
+            }
+            Some(policy) => (evaluate_retention(policy, entries, self.clock.now()), None),
+        };
+
+        let eligible_count = archives
+            .iter()
+            .filter(|archive| archive.decision.is_prune_eligible())
+            .count();
+        RetentionEvaluation {
+            vault_id: vault_id.to_string(),
+            policy,
+            archives,
+            summary: RetentionSummary {
+                eligible_count,
+                ..RetentionSummary::default()
+            },
+            skipped_reason,
+        }
+    }
+}
+
+impl Default for RetentionService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn load_settings() -> VaultResult<VaultSettingsRegistry> {
+    VaultSettingsRegistry::load().map_err(|e| VaultError::StorageError(e.to_string()))
+}
+
+fn save_settings(settings: &VaultSettingsRegistry) -> VaultResult<()> {
+    settings
+        .save()
+        .map_err(|e| VaultError::StorageError(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::shared::infrastructure::clock::testing::{FakeClock, service};
+    use crate::services::vault::domain::models::{RetentionDecision, RetentionRule};
+    use chrono::{Duration, Utc};
+
+    fn entries() -> Vec<ArchiveIndexEntry> {
+        let newest: chrono::DateTime<Utc> = "2026-03-01T00:00:00Z".parse().unwrap();
+        (0..4)
+            .map(|n| ArchiveIndexEntry {
+                archive_id: format!("archive-{n}"),
+                vault_id: "vault-001".to_string(),
+                archive_name: "Scratch.age".to_string(),
+                encryption_revision: n,
+                created_at: newest - Duration::days(i64::from(3 - n)),
+                file_count: 1,
+                comment: None,
+                comment_updated_at: None,
+                immutable: false,
+                parity: None,
+                migrated_from: None,
+                prune_marked: false,
+                remapped_from: None,
+                archive_sha256: None,
+                recipients: Vec::new(),
+            })
+            .collect()
+    }
+
+    fn last_two() -> Option<RetentionPolicy> {
+        Some(RetentionPolicy {
+            keep_last_n: 2,
+            ..RetentionPolicy::default()
+        })
+    }
+
+    #[test]
+    fn test_classify_counts_eligible_archives() {
+        let retention =
+            RetentionService::with_clock(service(&FakeClock::at("2026-03-02T00:00:00Z")));
+
+        let evaluation = retention.classify("vault-001", last_two(), &entries());
+
+        assert_eq!(evaluation.summary.eligible_count, 2);
+        assert_eq!(evaluation.eligible_ids(), ["archive-0", "archive-1"]);
+        assert!(evaluation.skipped_reason.is_none());
+    }
+
+    #[test]
+    fn test_nothing_is_eligible_without_policy_or_reliable_clock() {
+        let unreliable =
+            RetentionService::with_clock(service(&FakeClock::at("2001-01-01T00:00:00Z")));
+        let reliable =
+            RetentionService::with_clock(service(&FakeClock::at("2026-03-02T00:00:00Z")));
+
+        let skipped = unreliable.classify("vault-001", last_two(), &entries());
+        let unset = reliable.classify("vault-001", None, &entries());
+
+        for evaluation in [&skipped, &unset] {
+            assert_eq!(evaluation.summary.eligible_count, 0);
+            assert!(evaluation.archives.iter().all(|archive| archive.decision
+                == RetentionDecision::Retained {
+                    rule: RetentionRule::Unevaluated
+                }));
+        }
+        assert!(skipped.skipped_reason.is_some());
+        assert!(unset.skipped_reason.is_none());
+    }
+}
@@ -0,0 +1,115 @@
+//! Shared Store Service
+//!
+//! Points this profile at a vault store shared with other OS accounts,
+//! reports who is changing the store and clears the lock of a holder that's
+//! gone. The lock itself is taken by every write into the store (see
+//! `io::store_lock`).
+
+use crate::prelude::*;
+use crate::services::shared::infrastructure::get_app_dir;
+use crate::services::shared::infrastructure::io::StoreLock;
+use crate::services::shared::infrastructure::path_management::{
+    STORE_MANIFESTS_DIR, get_shared_store_dir, set_shared_store_dir,
+};
+use crate::services::vault::domain::models::SharedStoreStatus;
+use crate::services::vault::domain::{VaultError, VaultResult};
+use crate::services::vault::infrastructure::persistence::vault_persistence::manifests_in;
+use chrono::Utc;
+use std::path::Path;
+
+#[derive(Debug)]
+pub struct SharedStoreService;
+
+impl SharedStoreService {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// This profile's shared store, if any, and who holds its lock
+    pub fn status(&self) -> VaultResult<SharedStoreStatus> {
+        let store_dir = get_shared_store_dir().map_err(storage)?;
+        status_of(store_dir.as_deref())
+    }
+
+    /// Use `store_dir` as the shared store, or stop sharing with `None`
+    ///
+    /// Vaults already in this profile stay there; vaults created from now on
+    /// go to the store.
+    pub fn set_store(&self, store_dir: Option<&Path>) -> VaultResult<SharedStoreStatus> {
+        let store_dir = set_shared_store_dir(store_dir)
+            .map_err(|e| VaultError::InvalidOperation(e.to_string()))?;
+        info!(shared = store_dir.is_some(), "Shared vault store changed");
+        status_of(store_dir.as_deref())
+    }
+
+    /// Clear the store lock of a holder that's no longer around
+    ///
+    /// Fails with `StoreInUseBy` while the holder is still active.
+    pub fn take_over(&self) -> VaultResult<SharedStoreStatus> {
+        let store_dir = get_shared_store_dir().map_err(storage)?.ok_or_else(|| {
+            VaultError::InvalidOperation(
+                "This profile doesn't use a shared vault store".to_string(),
+            )
+        })?;
+        let profile_dir = get_app_dir().map_err(storage)?;
+
+        // Released as soon as it's taken; the next change locks as usual
+        drop(StoreLock::take_over(&store_dir, &profile_dir)?);
+        status_of(Some(&store_dir))
+    }
+}
+
+impl Default for SharedStoreService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn status_of(store_dir: Option<&Path>) -> VaultResult<SharedStoreStatus> {
+    let Some(store_dir) = store_dir else {
+        return Ok(SharedStoreStatus {
+            store_dir: None,
+            shared_vault_count: 0,
+            holder: None,
+            holder_stale: false,
+        });
+    };
+
+    let holder = StoreLock::holder(store_dir)?;
+    Ok(SharedStoreStatus {
+        store_dir: Some(store_dir.display().to_string()),
+        shared_vault_count: manifests_in(&store_dir.join(STORE_MANIFESTS_DIR)).len(),
+        holder_stale: holder
+            .as_ref()
+            .is_some_and(|holder| holder.is_stale(Utc::now())),
+        holder,
+    })
+}
+
+fn storage(e: impl std::fmt::Display) -> VaultError {
+    VaultError::StorageError(e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_status_names_the_other_profile() {
+        let temp = TempDir::new().unwrap();
+        let store = temp.path().join("Shared");
+        let alice = temp.path().join("alice");
+        std::fs::create_dir_all(store.join(STORE_MANIFESTS_DIR)).unwrap();
+
+        assert_eq!(status_of(None).unwrap().store_dir, None);
+        let idle = status_of(Some(&store)).unwrap();
+        assert_eq!(idle.holder, None);
+        assert_eq!(idle.shared_vault_count, 0);
+
+        let held = StoreLock::acquire(&store, &alice).unwrap();
+        let busy = status_of(Some(&store)).unwrap();
+        assert_eq!(busy.holder.as_ref(), Some(held.owner()));
+        assert!(!busy.holder_stale);
+    }
+}
@@ -0,0 +1,475 @@
+//! Shutdown Service
+//!
+//! Keeps the app from quitting out from under a running operation. Closing
+//! the last window (or the OS ending the app) asks `check` first; while
+//! anything runs the UI gets the list and can offer a forced quit, which:
+//!
+//! 1. asks every running operation to stop,
+//! 2. waits a bounded time for them to clean up and unregister,
+//! 3. writes an interrupted-operation record to each affected vault's
+//!    local settings,
+//! 4. and only then lets the app exit.
+//!
+//! The next start (`review_interrupted`) notes which leftover archive files
+//! bootstrap quarantined, and the notification digest reports the records
+//! until the user dismisses them or re-runs the encryption.
+
+use crate::prelude::*;
+use crate::services::shared::infrastructure::progress::{
+    OperationKind, RunningOperation, cancel_operations, get_global_progress, running_operations,
+    wait_until_idle,
+};
+use crate::services::vault::domain::models::{
+    ActiveOperationInfo, ForcedQuitReport, IncompleteArchiveReport, InterruptedOperation,
+    ShutdownCheck,
+};
+use crate::services::vault::domain::{VaultError, VaultResult};
+use crate::services::vault::infrastructure::persistence::VaultSettingsRegistry;
+use crate::types::{DurationMs, ProgressUpdate};
+use chrono::Utc;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+/// App-wide event carrying a `ShutdownCheck` when a quit was held back
+pub const SHUTDOWN_BLOCKED_EVENT: &str = "shutdown-blocked";
+
+/// How long a forced quit waits for cancelled operations to clean up
+pub const FORCED_QUIT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Set once a forced quit has finished, so the exit it triggers goes through
+static EXIT_ALLOWED: AtomicBool = AtomicBool::new(false);
+
+/// Whether a forced quit already ran and the app may exit unchecked
+pub fn exit_allowed() -> bool {
+    EXIT_ALLOWED.load(Ordering::SeqCst)
+}
+
+/// The operation registry as the shutdown coordinator sees it
+pub trait OperationControl: Send + Sync {
+    /// Running operations, oldest first
+    fn running(&self) -> Vec<RunningOperation>;
+    /// Latest progress published under `progress_id`
+    fn progress(&self, progress_id: &str) -> Option<ProgressUpdate>;
+    /// Ask every running operation to stop
+    fn cancel(&self) -> Vec<OperationKind>;
+    /// Wait for every operation to end; false on timeout
+    fn wait_until_idle(&self, timeout: Duration) -> bool;
+}
+
+/// This process's operation registry
+pub(super) struct ProcessOperations;
+
+impl OperationControl for ProcessOperations {
+    fn running(&self) -> Vec<RunningOperation> {
+        running_operations()
+    }
+
+    fn progress(&self, progress_id: &str) -> Option<ProgressUpdate> {
+        get_global_progress(progress_id)
+    }
+
+    fn cancel(&self) -> Vec<OperationKind> {
+        cancel_operations()
+    }
+
+    fn wait_until_idle(&self, timeout: Duration) -> bool {
+        wait_until_idle(timeout)
+    }
+}
+
+/// Service coordinating a safe quit
+pub struct ShutdownService {
+    operations: Box<dyn OperationControl>,
+    /// Vault settings location; the config directory's when `None`
+    settings_path: Option<PathBuf>,
+}
+
+impl ShutdownService {
+    pub fn new() -> Self {
+        Self {
+            operations: Box::new(ProcessOperations),
+            settings_path: None,
+        }
+    }
+
+    /// Service over the given registry and settings file
+    pub fn with(operations: Box<dyn OperationControl>, settings_path: PathBuf) -> Self {
+        Self {
+            operations,
+            settings_path: Some(settings_path),
+        }
+    }
+
+    /// What quitting now would interrupt
+    pub fn check(&self) -> ShutdownCheck {
+        let operations: Vec<ActiveOperationInfo> = self
+            .operations
+            .running()
+            .into_iter()
+            .map(|operation| {
+                let progress = operation
+                    .progress_id
+                    .as_deref()
+                    .and_then(|id| self.operations.progress(id));
+                ActiveOperationInfo {
+                    kind: operation.kind,
+                    vault_id: operation.vault_id,
+                    started_at: operation.started_at,
+                    progress: progress.as_ref().map(|p| p.progress),
+                    estimated_time_remaining: progress.and_then(|p| p.estimated_time_remaining),
+                }
+            })
+            .collect();
+
+        let estimated_time_remaining = operations
+            .iter()
+            .map(|o| o.estimated_time_remaining)
+            .collect::<Option<Vec<DurationMs>>>()
+            .and_then(|estimates| estimates.into_iter().max());
+
+        ShutdownCheck {
+            safe_to_quit: operations.is_empty(),
+            operations,
+            estimated_time_remaining,
+        }
+    }
+
+    /// Cancel everything running, wait up to `timeout` for it to clean up,
+    /// record the interruption per vault, then allow the app to exit
+    ///
+    /// A record that can't be written is logged; the quit goes ahead anyway.
+    pub fn force_quit(&self, timeout: Duration) -> ForcedQuitReport {
+        let running = self.operations.running();
+        let cancelled_operations = self.operations.cancel();
+        warn!(
+            operations = cancelled_operations.len(),
+            "Forced quit requested, cancelling running operations"
+        );
+
+        let stopped_in_time = self.operations.wait_until_idle(timeout);
+        if !stopped_in_time {
+            warn!(
+                timeout_secs = timeout.as_secs(),
+                "Operations still running at forced quit; quitting anyway"
+            );
+        }
+
+        let interrupted_vaults = match self.record_interrupted(&running, stopped_in_time) {
+            Ok(vaults) => vaults,
+            Err(e) => {
+                error!(error = %e, "Failed to record interrupted operations");
+                Vec::new()
+            }
+        };
+
+        EXIT_ALLOWED.store(true, Ordering::SeqCst);
+        info!(
+            cancelled = cancelled_operations.len(),
+            stopped_in_time,
+            vaults = interrupted_vaults.len(),
+            "Forced quit ready to exit"
+        );
+        ForcedQuitReport {
+            cancelled_operations,
+            stopped_in_time,
+            interrupted_vaults,
+        }
+    }
+
+    /// Note what happened to the output of operations the last run
+    /// interrupted, given this start's quarantine findings
+    ///
+    /// Returns each vault's records that were new to this start.
+    pub fn review_interrupted(
+        &self,
+        incomplete_archives: &[IncompleteArchiveReport],
+    ) -> VaultResult<Vec<(String, InterruptedOperation)>> {
+        let mut settings = self.load_settings()?;
+        let mut reviewed = Vec::new();
+        for (vault_id, vault_settings) in &mut settings.vaults {
+            let quarantined = incomplete_archives
+                .iter()
+                .filter(|report| &report.vault_id == vault_id)
+                .map(|report| {
+                    report
+                        .artifacts
+                        .iter()
+                        .filter(|a| a.quarantined_as.is_some())
+                        .count()
+                })
+                .sum();
+            for record in &mut vault_settings.interrupted_operations {
+                if record.quarantined_files.is_none() {
+                    record.quarantined_files = Some(quarantined);
+                    reviewed.push((vault_id.clone(), record.clone()));
+                }
+            }
+        }
+
+        if !reviewed.is_empty() {
+            self.save_settings(&settings)?;
+        }
+        Ok(reviewed)
+    }
+
+    fn record_interrupted(
+        &self,
+        running: &[RunningOperation],
+        stopped_in_time: bool,
+    ) -> VaultResult<Vec<String>> {
+        let interrupted_at = Utc::now();
+        let mut settings = self.load_settings()?;
+        let mut vaults: Vec<String> = Vec::new();
+        for operation in running {
+            let Some(vault_id) = &operation.vault_id else {
+                continue;
+            };
+            settings
+                .entry(vault_id)
+                .interrupted_operations
+                .push(InterruptedOperation {
+                    operation: operation.kind,
+                    interrupted_at,
+                    stopped_in_time,
+                    quarantined_files: None,
+                });
+            if !vaults.contains(vault_id) {
+                vaults.push(vault_id.clone());
+            }
+        }
+
+        if !vaults.is_empty() {
+            self.save_settings(&settings)?;
+        }
+        Ok(vaults)
+    }
+
+    fn load_settings(&self) -> VaultResult<VaultSettingsRegistry> {
+        match &self.settings_path {
+            Some(path) => VaultSettingsRegistry::load_from(path),
+            None => VaultSettingsRegistry::load(),
+        }
+        .map_err(|e| VaultError::StorageError(e.to_string()))
+    }
+
+    fn save_settings(&self, settings: &VaultSettingsRegistry) -> VaultResult<()> {
+        match &self.settings_path {
+            Some(path) => settings.save_to(path),
+            None => settings.save(),
+        }
+        .map_err(|e| VaultError::StorageError(e.to_string()))
+    }
+}
+
+impl Default for ShutdownService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::vault::domain::models::{IncompleteArtifact, IncompleteArtifactKind};
+    use crate::types::ByteSize;
+    use std::sync::{Arc, Condvar, Mutex};
+    use tempfile::TempDir;
+
+    /// Stand-in for the process-wide registry, which other tests share
+    #[derive(Clone, Default)]
+    struct FakeOperations {
+        state: Arc<(Mutex<FakeState>, Condvar)>,
+    }
+
+    #[derive(Default)]
+    struct FakeState {
+        running: Vec<RunningOperation>,
+        cancelled: bool,
+        events: Vec<String>,
+    }
+
+    impl FakeOperations {
+        fn start(&self, kind: OperationKind, vault_id: &str) {
+            self.state.0.lock().unwrap().running.push(RunningOperation {
+                kind,
+                vault_id: Some(vault_id.to_string()),
+                progress_id: Some(format!("{vault_id}-progress")),
+                started_at: Utc::now(),
+            });
+        }
+
+        /// A long operation: runs until cancelled, then `cleanup` and unregister
+        fn spawn_long(&self, cleanup: impl FnOnce() + Send + 'static) {
+            let fake = self.clone();
+            std::thread::spawn(move || {
+                let (lock, signal) = &*fake.state;
+                let state = signal
+                    .wait_while(lock.lock().unwrap(), |s| !s.cancelled)
+                    .unwrap();
+                drop(state);
+                cleanup();
+                let mut state = lock.lock().unwrap();
+                state.events.push("cleaned up".to_string());
+                state.running.clear();
+                signal.notify_all();
+            });
+        }
+
+        fn events(&self) -> Vec<String> {
+            self.state.0.lock().unwrap().events.clone()
+        }
+    }
+
+    impl OperationControl for FakeOperations {
+        fn running(&self) -> Vec<RunningOperation> {
+            self.state.0.lock().unwrap().running.clone()
+        }
+
+        fn progress(&self, progress_id: &str) -> Option<ProgressUpdate> {
+            (progress_id == "docs-progress").then(|| ProgressUpdate {
+                operation_id: progress_id.to_string(),
+                progress: 0.4,
+                message: "Encrypting files...".to_string(),
+                details: None,
+                timestamp: Utc::now(),
+                estimated_time_remaining: Some(DurationMs(90_000)),
+                io_priority: Default::default(),
+                trace_id: None,
+            })
+        }
+
+        fn cancel(&self) -> Vec<OperationKind> {
+            let (lock, signal) = &*self.state;
+            let mut state = lock.lock().unwrap();
+            state.cancelled = true;
+            state.events.push("cancelled".to_string());
+            signal.notify_all();
+            state.running.iter().map(|o| o.kind).collect()
+        }
+
+        fn wait_until_idle(&self, timeout: Duration) -> bool {
+            let (lock, signal) = &*self.state;
+            let (state, _) = signal
+                .wait_timeout_while(lock.lock().unwrap(), timeout, |s| !s.running.is_empty())
+                .unwrap();
+            state.running.is_empty()
+        }
+    }
+
+    #[test]
+    fn test_running_operation_blocks_quit() {
+        let temp = TempDir::new().unwrap();
+        let fake = FakeOperations::default();
+        let service = ShutdownService::with(
+            Box::new(fake.clone()),
+            temp.path().join("vault_settings.json"),
+        );
+        assert!(service.check().safe_to_quit);
+
+        fake.start(OperationKind::Encryption, "docs");
+        fake.start(OperationKind::BatchDecryption, "photos");
+        let check = service.check();
+        assert!(!check.safe_to_quit);
+        assert_eq!(check.operations.len(), 2);
+        assert_eq!(check.operations[0].kind, OperationKind::Encryption);
+        assert_eq!(check.operations[0].vault_id.as_deref(), Some("docs"));
+        assert_eq!(check.operations[0].progress, Some(0.4));
+        assert_eq!(
+            check.operations[0].estimated_time_remaining,
+            Some(DurationMs(90_000))
+        );
+        // One operation has no estimate, so neither does the quit
+        assert_eq!(check.estimated_time_remaining, None);
+    }
+
+    #[test]
+    fn test_forced_quit_cleans_up_before_recording() {
+        let temp = TempDir::new().unwrap();
+        let settings_path = temp.path().join("vault_settings.json");
+        let partial = temp.path().join("Documents.age.partial");
+        std::fs::write(&partial, b"partial archive").unwrap();
+
+        let fake = FakeOperations::default();
+        fake.start(OperationKind::Encryption, "docs");
+        fake.spawn_long({
+            let partial = partial.clone();
+            let settings_path = settings_path.clone();
+            move || {
+                // Nothing is recorded while the operation is still cleaning up
+                assert!(!settings_path.exists());
+                std::thread::sleep(Duration::from_millis(20));
+                std::fs::remove_file(&partial).unwrap();
+            }
+        });
+
+        let service = ShutdownService::with(Box::new(fake.clone()), settings_path.clone());
+        let report = service.force_quit(Duration::from_secs(5));
+
+        assert_eq!(report.cancelled_operations, vec![OperationKind::Encryption]);
+        assert!(report.stopped_in_time);
+        assert_eq!(report.interrupted_vaults, vec!["docs"]);
+        assert_eq!(fake.events(), ["cancelled", "cleaned up"]);
+        assert!(!partial.exists());
+        assert!(exit_allowed());
+
+        let settings = VaultSettingsRegistry::load_from(&settings_path).unwrap();
+        let records = settings.get("docs").interrupted_operations;
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].operation, OperationKind::Encryption);
+        assert!(records[0].stopped_in_time);
+        assert_eq!(records[0].quarantined_files, None);
+    }
+
+    #[test]
+    fn test_forced_quit_gives_up_after_timeout() {
+        let temp = TempDir::new().unwrap();
+        let settings_path = temp.path().join("vault_settings.json");
+        let fake = FakeOperations::default();
+        // Ignores cancellation
+        fake.start(OperationKind::Encryption, "docs");
+
+        let service = ShutdownService::with(Box::new(fake), settings_path.clone());
+        let report = service.force_quit(Duration::from_millis(20));
+
+        assert!(!report.stopped_in_time);
+        let settings = VaultSettingsRegistry::load_from(&settings_path).unwrap();
+        assert!(!settings.get("docs").interrupted_operations[0].stopped_in_time);
+    }
+
+    #[test]
+    fn test_next_start_notes_quarantined_output() {
+        let temp = TempDir::new().unwrap();
+        let settings_path = temp.path().join("vault_settings.json");
+        let fake = FakeOperations::default();
+        fake.start(OperationKind::Encryption, "docs");
+        let service = ShutdownService::with(Box::new(fake), settings_path.clone());
+        service.force_quit(Duration::from_millis(1));
+
+        // Bootstrap quarantined the half-written archive
+        let incomplete = [IncompleteArchiveReport {
+            vault_id: "docs".to_string(),
+            artifacts: vec![IncompleteArtifact {
+                file_name: "Documents.age.partial".to_string(),
+                kind: IncompleteArtifactKind::StagedPartial,
+                size: ByteSize(15),
+                quarantined_as: Some("quarantine/Documents.age.partial".to_string()),
+                skipped_reason: None,
+            }],
+            removed_index_entries: vec![],
+            quarantine_dir: "quarantine".to_string(),
+        }];
+        let reviewed = service.review_interrupted(&incomplete).unwrap();
+        assert_eq!(reviewed.len(), 1);
+        assert_eq!(reviewed[0].0, "docs");
+        assert_eq!(reviewed[0].1.quarantined_files, Some(1));
+
+        // Already reviewed records aren't reported again
+        assert!(service.review_interrupted(&[]).unwrap().is_empty());
+        let settings = VaultSettingsRegistry::load_from(&settings_path).unwrap();
+        assert_eq!(
+            settings.get("docs").interrupted_operations[0].quarantined_files,
+            Some(1)
+        );
+    }
+}
@@ -0,0 +1,278 @@
+//! Statistics History Service
+//!
+//! Records a vault's statistics after each change to its archives (an
+//! encryption or a prune) and serves the series for trend charts. Recording
+//! never fails the operation that triggered it: a history that can't be
+//! read or saved is logged and left alone. Nothing is recorded while the
+//! clock is unreliable, so a wrong date can't bend the trend.
+//!
+//! History is keyed by vault ID, so it carries across renames and moves, and
+//! it goes when the vault is deleted.
+
+use crate::prelude::*;
+use crate::services::shared::infrastructure::{ClockService, get_vaults_directory};
+use crate::services::vault::domain::models::{
+    DEFAULT_CHART_POINTS, MAX_CHART_POINTS, StatisticsRange, StatisticsRecord, StatisticsTrend,
+    VaultStatisticsHistory, downsample,
+};
+use crate::services::vault::domain::{VaultError, VaultResult};
+use crate::services::vault::infrastructure::persistence::{ArchiveIndex, StatisticsHistory};
+use std::path::Path;
+
+/// Service for per-vault statistics history
+#[derive(Debug)]
+pub struct StatisticsHistoryService {
+    clock: ClockService,
+}
+
+impl StatisticsHistoryService {
+    pub fn new() -> Self {
+        Self::with_clock(ClockService::global().clone())
+    }
+
+    pub fn with_clock(clock: ClockService) -> Self {
+        Self { clock }
+    }
+
+    /// Record a vault's archives as they stand in `index` and on disk
+    pub fn record(&self, index: &ArchiveIndex, vault_id: &str) {
+        let vaults_dir = match get_vaults_directory() {
+            Ok(dir) => dir,
+            Err(e) => {
+                warn!(vault_id, error = %e, "Couldn't record vault statistics");
+                return;
+            }
+        };
+        update("record", |history| {
+            self.record_in(history, index, &vaults_dir, vault_id);
+        });
+    }
+
+    /// Drop a deleted vault's history
+    pub fn remove_vault(&self, vault_id: &str) {
+        update("remove_vault", |history| {
+            history.remove_vault(vault_id);
+        });
+    }
+
+    /// A vault's series over `range`, downsampled to at most `max_points`
+    pub fn history(
+        &self,
+        vault_id: &str,
+        range: StatisticsRange,
+        max_points: Option<usize>,
+    ) -> VaultResult<VaultStatisticsHistory> {
+        let history =
+            StatisticsHistory::load().map_err(|e| VaultError::StorageError(e.to_string()))?;
+        Ok(self.series(&history, vault_id, range, max_points))
+    }
+
+    /// Figures derived from a vault's history (empty if it can't be read)
+    pub fn trend(&self, vault_id: &str) -> StatisticsTrend {
+        match StatisticsHistory::load() {
+            Ok(history) => {
+                StatisticsTrend::from_records(history.records(vault_id), self.clock.now())
+            }
+            Err(e) => {
+                warn!(vault_id, error = %e, "Couldn't read statistics history");
+                StatisticsTrend::default()
+            }
+        }
+    }
+
+    /// Append a record of `vault_id`'s archives to `history`
+    pub fn record_in(
+        &self,
+        history: &mut StatisticsHistory,
+        index: &ArchiveIndex,
+        vaults_dir: &Path,
+        vault_id: &str,
+    ) {
+        if !self.clock.is_reliable() {
+            debug!(vault_id, "Clock unreliable, not recording vault statistics");
+            return;
+        }
+        let size_of = |name: &str| {
+            std::fs::metadata(vaults_dir.join(name))
+                .ok()
+                .map(|metadata| metadata.len())
+        };
+        let record = StatisticsRecord::snapshot(index.entries(vault_id), size_of, self.clock.now());
+        debug!(
+            vault_id,
+            archive_count = record.archive_count,
+            total_encrypted = record.total_encrypted.bytes(),
+            "Recorded vault statistics"
+        );
+        history.append(vault_id, record);
+    }
+
+    /// `vault_id`'s records in `range`, downsampled for a chart
+    pub fn series(
+        &self,
+        history: &StatisticsHistory,
+        vault_id: &str,
+        range: StatisticsRange,
+        max_points: Option<usize>,
+    ) -> VaultStatisticsHistory {
+        let since = range.since(self.clock.now());
+        let records: Vec<StatisticsRecord> = history
+            .records(vault_id)
+            .iter()
+            .filter(|record| since.is_none_or(|since| record.recorded_at >= since))
+            .cloned()
+            .collect();
+        let max_points = max_points
+            .unwrap_or(DEFAULT_CHART_POINTS)
+            .clamp(1, MAX_CHART_POINTS);
+
+        VaultStatisticsHistory {
+            vault_id: vault_id.to_string(),
+            range,
+            points: downsample(&records, max_points),
+            total_records: records.len(),
+        }
+    }
+}
+
+impl Default for StatisticsHistoryService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn update(operation: &str, change: impl FnOnce(&mut StatisticsHistory)) {
+    let path = match StatisticsHistory::get_history_path() {
+        Ok(path) => path,
+        Err(e) => {
+            warn!(operation, error = %e, "Couldn't update statistics history");
+            return;
+        }
+    };
+    let mut history = match StatisticsHistory::load_from(&path) {
+        Ok(history) => history,
+        Err(e) => {
+            warn!(operation, error = %e, "Couldn't read statistics history");
+            return;
+        }
+    };
+    change(&mut history);
+    if let Err(e) = history.save_to(&path) {
+        warn!(operation, error = %e, "Couldn't save statistics history");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::shared::infrastructure::Clock;
+    use crate::services::shared::infrastructure::clock::testing::{FakeClock, service};
+    use crate::services::vault::domain::models::ArchiveIndexEntry;
+    use crate::types::ByteSize;
+    use std::time::Duration;
+    use tempfile::TempDir;
+
+    const DAY: Duration = Duration::from_secs(24 * 60 * 60);
+
+    fn entry(archive_id: &str, archive_name: &str, clock: &FakeClock) -> ArchiveIndexEntry {
+        ArchiveIndexEntry {
+            archive_id: archive_id.to_string(),
+            vault_id: "vault-001".to_string(),
+            archive_name: archive_name.to_string(),
+            encryption_revision: 1,
+            created_at: clock.now(),
+            file_count: 2,
+            comment: None,
+            comment_updated_at: None,
+            immutable: false,
+            parity: None,
+            migrated_from: None,
+            prune_marked: false,
+            remapped_from: None,
+            archive_sha256: None,
+            recipients: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_history_continues_across_rename() {
+        let temp_dir = TempDir::new().unwrap();
+        let clock = FakeClock::at("2026-03-01T00:00:00Z");
+        let stats = StatisticsHistoryService::with_clock(service(&clock));
+        let mut index = ArchiveIndex::default();
+        let mut history = StatisticsHistory::default();
+
+        std::fs::write(temp_dir.path().join("Family.age"), vec![0u8; 1000]).unwrap();
+        index.record(entry("a1", "Family.age", &clock));
+        stats.record_in(&mut history, &index, temp_dir.path(), "vault-001");
+
+        // The vault is renamed; its next archive is written under the new name
+        clock.advance(DAY * 40);
+        std::fs::rename(
+            temp_dir.path().join("Family.age"),
+            temp_dir.path().join("Family-Papers.age"),
+        )
+        .unwrap();
+        index.vaults.get_mut("vault-001").unwrap()[0].archive_name =
+            "Family-Papers.age".to_string();
+        std::fs::write(temp_dir.path().join("Family-Papers-2.age"), vec![0u8; 3000]).unwrap();
+        index.record(entry("a2", "Family-Papers-2.age", &clock));
+        stats.record_in(&mut history, &index, temp_dir.path(), "vault-001");
+
+        let series = stats.series(&history, "vault-001", StatisticsRange::All, None);
+        assert_eq!(series.total_records, 2);
+        let totals: Vec<_> = series.points.iter().map(|p| p.total_encrypted).collect();
+        assert_eq!(totals, vec![ByteSize(1000), ByteSize(4000)]);
+        assert_eq!(series.points[1].largest_archive, ByteSize(3000));
+
+        let trend = StatisticsTrend::from_records(history.records("vault-001"), clock.now());
+        assert_eq!(trend.growth_30d, Some(ByteSize(3000)));
+        assert_eq!(trend.average_archive_size, Some(ByteSize(2000)));
+
+        // A month's range leaves out the record from before the rename
+        let series = stats.series(&history, "vault-001", StatisticsRange::Month, None);
+        assert_eq!(series.total_records, 1);
+    }
+
+    #[test]
+    fn test_series_is_downsampled() {
+        let temp_dir = TempDir::new().unwrap();
+        let clock = FakeClock::at("2026-03-01T00:00:00Z");
+        let stats = StatisticsHistoryService::with_clock(service(&clock));
+        let index = ArchiveIndex::default();
+        let mut history = StatisticsHistory::default();
+
+        for _ in 0..100 {
+            stats.record_in(&mut history, &index, temp_dir.path(), "vault-001");
+            clock.advance(DAY);
+        }
+
+        let series = stats.series(&history, "vault-001", StatisticsRange::All, Some(10));
+        assert_eq!(series.total_records, 100);
+        assert_eq!(series.points.len(), 10);
+        assert_eq!(series.points.last(), history.records("vault-001").last());
+
+        // Requests are held to the chart limits
+        let series = stats.series(&history, "vault-001", StatisticsRange::All, Some(0));
+        assert_eq!(series.points.len(), 1);
+        let series = stats.series(&history, "vault-001", StatisticsRange::Week, Some(1000));
+        assert_eq!(series.points.len(), 7);
+    }
+
+    #[test]
+    fn test_unreliable_clock_records_nothing() {
+        let temp_dir = TempDir::new().unwrap();
+        let clock = FakeClock::at("2026-03-01T00:00:00Z");
+        let stats = StatisticsHistoryService::with_clock(service(&clock));
+        let mut history = StatisticsHistory::default();
+
+        clock.set_wall("1970-01-01T00:00:00Z");
+        stats.record_in(
+            &mut history,
+            &ArchiveIndex::default(),
+            temp_dir.path(),
+            "vault-001",
+        );
+        assert!(history.records("vault-001").is_empty());
+    }
+}
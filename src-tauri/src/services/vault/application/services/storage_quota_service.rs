@@ -0,0 +1,704 @@
+//! Storage Quota Service
+//!
+//! Reports how much space each category of app data takes and keeps each
+//! within its quota (see `StorageQuotas`). When a category is over quota,
+//! the least recently used items eligible for purging are deleted, oldest
+//! first, until it fits:
+//!
+//! - Staging: leftovers of interrupted runs; a running operation's staging
+//!   area is never touched
+//! - Quarantine: files quarantined at least `QUARANTINE_MIN_AGE_DAYS` ago
+//! - Snapshots: all but the newest, removed through the snapshot index
+//! - Cache: everything
+//! - Logs: everything but the file the app is writing to
+//! - Trash: items deleted at least `TRASH_RETENTION_DAYS` ago
+//!
+//! Ages come from the clock, so nothing that depends on one is purged while
+//! the clock is unreliable. Every purge is logged.
+
+use super::quarantine_service::{quarantined_files, read_record, record_path};
+use crate::logging::LOG_FILE_NAME;
+use crate::prelude::*;
+use crate::services::file::infrastructure::file_operations::{STAGING_DIR, is_live_staging};
+use crate::services::shared::infrastructure::path_management::SensitiveDirectory;
+use crate::services::shared::infrastructure::{
+    ClockService, SecureDeleteService, get_app_dir, get_cache_dir, get_keys_dir, get_logs_dir,
+    get_vaults_directory,
+};
+use crate::services::vault::application::services::QUARANTINE_DIR;
+use crate::services::vault::domain::models::{
+    CategoryUsage, PurgedStorageItem, SensitiveDirectoryKind, SensitiveDirectoryStatus,
+    StorageCategory, StorageCleanupReport, StorageQuotas, StorageUsageReport,
+};
+use crate::services::vault::domain::{VaultError, VaultResult};
+use crate::services::vault::infrastructure::persistence::{AppConfig, MetadataSnapshotStore};
+use crate::types::ByteSize;
+use chrono::{DateTime, Utc};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Folder under the app data directory holding deleted items
+pub const TRASH_DIR: &str = "trash";
+
+/// Days a deleted item stays in the trash before it can be purged
+pub const TRASH_RETENTION_DAYS: i64 = 30;
+
+/// Days a file stays in quarantine before quota cleanup may purge it
+pub const QUARANTINE_MIN_AGE_DAYS: i64 = 30;
+
+/// Where each category's data lives
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StorageLocations {
+    pub staging: PathBuf,
+    pub quarantine: PathBuf,
+    pub snapshots: PathBuf,
+    pub cache: PathBuf,
+    pub logs: PathBuf,
+    pub trash: PathBuf,
+}
+
+impl StorageLocations {
+    /// The standard locations
+    ///
+    /// Quarantine sits beside the archives it came from, in the vaults
+    /// directory; the rest is app data.
+    pub fn resolve() -> VaultResult<Self> {
+        let storage_error = |e: crate::error::StorageError| VaultError::StorageError(e.to_string());
+        let app_dir = get_app_dir().map_err(storage_error)?;
+        Ok(Self {
+            staging: app_dir.join(STAGING_DIR),
+            quarantine: get_vaults_directory()
+                .map_err(storage_error)?
+                .join(QUARANTINE_DIR),
+            snapshots: MetadataSnapshotStore::open()
+                .map_err(storage_error)?
+                .root()
+                .to_path_buf(),
+            cache: get_cache_dir().map_err(storage_error)?,
+            logs: get_logs_dir().map_err(storage_error)?,
+            trash: app_dir.join(TRASH_DIR),
+        })
+    }
+
+    pub fn dir(&self, category: StorageCategory) -> &Path {
+        match category {
+            StorageCategory::Staging => &self.staging,
+            StorageCategory::Quarantine => &self.quarantine,
+            StorageCategory::Snapshots => &self.snapshots,
+            StorageCategory::Cache => &self.cache,
+            StorageCategory::Logs => &self.logs,
+            StorageCategory::Trash => &self.trash,
+        }
+    }
+}
+
+/// How an item is deleted
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum ItemHandle {
+    /// File or directory, deleted as a whole
+    Path(PathBuf),
+    /// Quarantined file, deleted together with its record
+    Quarantined(PathBuf),
+    Snapshot(String),
+}
+
+/// Something a category holds that could be purged
+#[derive(Debug, Clone)]
+struct StorageItem {
+    handle: ItemHandle,
+    name: String,
+    size: u64,
+    last_used: DateTime<Utc>,
+    eligible: bool,
+}
+
+/// Service for reporting and enforcing app data quotas
+#[derive(Debug)]
+pub struct StorageQuotaService {
+    clock: ClockService,
+    deleter: SecureDeleteService,
+}
+
+impl StorageQuotaService {
+    pub fn new() -> Self {
+        Self::with_clock(ClockService::global().clone())
+    }
+
+    pub fn with_clock(clock: ClockService) -> Self {
+        Self {
+            clock,
+            deleter: SecureDeleteService::new(),
+        }
+    }
+
+    /// Usage of each category in the standard locations
+    pub fn usage(&self) -> VaultResult<StorageUsageReport> {
+        self.usage_in(&StorageLocations::resolve()?, &configured_quotas())
+    }
+
+    /// Whether each directory holding keys or plaintext is kept out of the
+    /// search index
+    ///
+    /// `session_dirs` are the output directories of pending cleanup
+    /// sessions.
+    pub fn indexing_protection(
+        &self,
+        session_dirs: &[PathBuf],
+    ) -> VaultResult<Vec<SensitiveDirectoryStatus>> {
+        let locations = StorageLocations::resolve()?;
+        let keys_dir = get_keys_dir().map_err(|e| VaultError::StorageError(e.to_string()))?;
+        Ok(sensitive_directories(&locations, &keys_dir, session_dirs)
+            .into_iter()
+            .map(|(kind, path)| SensitiveDirectoryStatus {
+                kind,
+                protection: path.is_dir().then(|| SensitiveDirectory::status(&path)),
+                path: path.display().to_string(),
+            })
+            .collect())
+    }
+
+    /// Purge eligible items from every category over its quota
+    pub fn enforce_quotas(&self) -> VaultResult<StorageCleanupReport> {
+        let report = self.enforce_in(&StorageLocations::resolve()?, &configured_quotas())?;
+        if !report.purged.is_empty() {
+            info!(
+                purged = report.purged.len(),
+                freed = report.freed.bytes(),
+                "Storage cleanup freed space"
+            );
+        }
+        Ok(report)
+    }
+
+    fn usage_in(
+        &self,
+        locations: &StorageLocations,
+        quotas: &StorageQuotas,
+    ) -> VaultResult<StorageUsageReport> {
+        let mut categories = Vec::with_capacity(StorageCategory::ALL.len());
+        for category in StorageCategory::ALL {
+            let dir = locations.dir(category);
+            let items = self.items(category, dir)?;
+            let size = dir_size(dir);
+            let eligible: Vec<&StorageItem> = items.iter().filter(|item| item.eligible).collect();
+            let quota = quotas.quota(category);
+            categories.push(CategoryUsage {
+                category,
+                path: dir.display().to_string(),
+                size: ByteSize(size),
+                item_count: items.len(),
+                purge_eligible: ByteSize(eligible.iter().map(|item| item.size).sum()),
+                eligible_items: eligible.len(),
+                purge_rule: category.purge_rule().to_string(),
+                quota,
+                over_quota: size > quota.bytes(),
+            });
+        }
+
+        Ok(StorageUsageReport {
+            total: categories
+                .iter()
+                .fold(ByteSize::ZERO, |total, usage| total + usage.size),
+            total_purge_eligible: categories
+                .iter()
+                .fold(ByteSize::ZERO, |total, usage| total + usage.purge_eligible),
+            categories,
+        })
+    }
+
+    fn enforce_in(
+        &self,
+        locations: &StorageLocations,
+        quotas: &StorageQuotas,
+    ) -> VaultResult<StorageCleanupReport> {
+        let mut report = StorageCleanupReport::default();
+        for category in StorageCategory::ALL {
+            let dir = locations.dir(category);
+            let quota = quotas.quota(category).bytes();
+            let mut used = dir_size(dir);
+            if used <= quota {
+                continue;
+            }
+
+            let mut items: Vec<StorageItem> = self
+                .items(category, dir)?
+                .into_iter()
+                .filter(|item| item.eligible)
+                .collect();
+            items.sort_by(|a, b| a.last_used.cmp(&b.last_used).then(a.name.cmp(&b.name)));
+
+            for item in items {
+                if used <= quota {
+                    break;
+                }
+                match self.purge(dir, &item) {
+                    Ok(freed) => {
+                        info!(
+                            ?category,
+                            item = %item.name,
+                            freed,
+                            "Purged item over storage quota"
+                        );
+                        used = used.saturating_sub(freed);
+                        report.freed = report.freed + ByteSize(freed);
+                        report.purged.push(PurgedStorageItem {
+                            category,
+                            name: item.name,
+                            freed: ByteSize(freed),
+                        });
+                    }
+                    Err(e) => {
+                        warn!(?category, item = %item.name, error = %e, "Failed to purge item")
+                    }
+                }
+            }
+
+            if used > quota {
+                warn!(
+                    ?category,
+                    used, quota, "Still over storage quota; nothing else can be purged"
+                );
+                report.still_over_quota.push(category);
+            }
+        }
+        Ok(report)
+    }
+
+    /// Items of `category` in `dir`, with their purge eligibility
+    fn items(&self, category: StorageCategory, dir: &Path) -> VaultResult<Vec<StorageItem>> {
+        if !dir.exists() {
+            return Ok(Vec::new());
+        }
+        match category {
+            StorageCategory::Staging => Ok(entries(dir)?
+                .into_iter()
+                .map(|(path, item)| StorageItem {
+                    eligible: !is_live_staging(&path),
+                    ..item
+                })
+                .collect()),
+            StorageCategory::Quarantine => self.quarantined_items(dir),
+            StorageCategory::Snapshots => snapshot_items(dir),
+            StorageCategory::Cache => Ok(entries(dir)?.into_iter().map(|(_, item)| item).collect()),
+            StorageCategory::Logs => Ok(entries(dir)?
+                .into_iter()
+                .map(|(_, item)| StorageItem {
+                    eligible: item.name != LOG_FILE_NAME,
+                    ..item
+                })
+                .collect()),
+            StorageCategory::Trash => Ok(entries(dir)?
+                .into_iter()
+                .map(|(_, item)| StorageItem {
+                    eligible: self.is_at_least_days_old(item.last_used, TRASH_RETENTION_DAYS),
+                    ..item
+                })
+                .collect()),
+        }
+    }
+
+    /// Files in every vault's quarantine, aged by their records
+    fn quarantined_items(&self, dir: &Path) -> VaultResult<Vec<StorageItem>> {
+        let mut items = Vec::new();
+        for vault_dir in fs::read_dir(dir)
+            .map_err(|e| VaultError::StorageError(e.to_string()))?
+            .filter_map(|e| e.ok())
+            .map(|e| e.path())
+            .filter(|path| path.is_dir())
+        {
+            for path in quarantined_files(&vault_dir)? {
+                let size = file_size(&path) + file_size(&record_path(&path));
+                // Files without a readable record are never purged
+                let (last_used, eligible) = match read_record(&path) {
+                    Some(record) => (
+                        record.quarantined_at,
+                        self.is_at_least_days_old(record.quarantined_at, QUARANTINE_MIN_AGE_DAYS),
+                    ),
+                    None => (modified_at(&path), false),
+                };
+                items.push(StorageItem {
+                    name: path
+                        .strip_prefix(dir)
+                        .unwrap_or(&path)
+                        .to_string_lossy()
+                        .to_string(),
+                    handle: ItemHandle::Quarantined(path),
+                    size,
+                    last_used,
+                    eligible,
+                });
+            }
+        }
+        Ok(items)
+    }
+
+    fn is_at_least_days_old(&self, at: DateTime<Utc>, days: i64) -> bool {
+        self.clock.days_since(at).is_some_and(|age| age >= days)
+    }
+
+    /// Delete an item, returning the bytes freed
+    fn purge(&self, dir: &Path, item: &StorageItem) -> VaultResult<u64> {
+        let storage_error = |e: std::io::Error| VaultError::StorageError(e.to_string());
+        match &item.handle {
+            ItemHandle::Path(path) if path.is_dir() => {
+                self.deleter.delete_dir_all(path).map_err(storage_error)?;
+            }
+            ItemHandle::Path(path) => {
+                self.deleter.delete_file(path).map_err(storage_error)?;
+            }
+            ItemHandle::Quarantined(path) => {
+                self.deleter.delete_file(path).map_err(storage_error)?;
+                let _ = self.deleter.delete_file(&record_path(path));
+            }
+            ItemHandle::Snapshot(id) => {
+                return MetadataSnapshotStore::at(dir)
+                    .remove(std::slice::from_ref(id))
+                    .map_err(|e| VaultError::StorageError(e.to_string()));
+            }
+        }
+        Ok(item.size)
+    }
+}
+
+impl Default for StorageQuotaService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Quotas from the app config, or the defaults if it can't be read
+fn configured_quotas() -> StorageQuotas {
+    match AppConfig::load() {
+        Ok(config) => config.storage_quotas,
+        Err(e) => {
+            warn!(error = %e, "Failed to load app config, using default storage quotas");
+            StorageQuotas::default()
+        }
+    }
+}
+
+/// Snapshots as items; the newest one is always kept
+fn snapshot_items(dir: &Path) -> VaultResult<Vec<StorageItem>> {
+    let snapshots = MetadataSnapshotStore::at(dir)
+        .exclusive_sizes()
+        .map_err(|e| VaultError::StorageError(e.to_string()))?;
+    let newest = snapshots
+        .iter()
+        .map(|(snapshot, _)| snapshot.created_at)
+        .max();
+    Ok(snapshots
+        .into_iter()
+        .map(|(snapshot, size)| StorageItem {
+            eligible: Some(snapshot.created_at) != newest,
+            name: snapshot.id.clone(),
+            handle: ItemHandle::Snapshot(snapshot.id),
+            size,
+            last_used: snapshot.created_at,
+        })
+        .collect())
+}
+
+/// Directories holding keys or plaintext; quarantine is protected per vault
+fn sensitive_directories(
+    locations: &StorageLocations,
+    keys_dir: &Path,
+    session_dirs: &[PathBuf],
+) -> Vec<(SensitiveDirectoryKind, PathBuf)> {
+    let mut dirs = vec![
+        (SensitiveDirectoryKind::Keys, keys_dir.to_path_buf()),
+        (SensitiveDirectoryKind::Staging, locations.staging.clone()),
+    ];
+    let mut quarantines: Vec<PathBuf> = fs::read_dir(&locations.quarantine)
+        .into_iter()
+        .flatten()
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|path| path.is_dir())
+        .collect();
+    quarantines.sort();
+    dirs.extend(
+        quarantines
+            .into_iter()
+            .map(|dir| (SensitiveDirectoryKind::Quarantine, dir)),
+    );
+    dirs.extend(
+        session_dirs
+            .iter()
+            .map(|dir| (SensitiveDirectoryKind::DecryptedSession, dir.clone())),
+    );
+    dirs
+}
+
+/// Top-level entries of `dir` as items, last used when last modified
+fn entries(dir: &Path) -> VaultResult<Vec<(PathBuf, StorageItem)>> {
+    let entries = fs::read_dir(dir).map_err(|e| VaultError::StorageError(e.to_string()))?;
+    Ok(entries
+        .filter_map(|e| e.ok())
+        .filter(|entry| !SensitiveDirectory::is_marker(&entry.file_name()))
+        .map(|entry| {
+            let path = entry.path();
+            let item = StorageItem {
+                handle: ItemHandle::Path(path.clone()),
+                name: entry.file_name().to_string_lossy().to_string(),
+                size: dir_size(&path),
+                last_used: modified_at(&path),
+                eligible: true,
+            };
+            (path, item)
+        })
+        .collect())
+}
+
+/// Bytes of all files at or under `path`
+fn dir_size(path: &Path) -> u64 {
+    walkdir::WalkDir::new(path)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|entry| entry.file_type().is_file())
+        .filter_map(|entry| entry.metadata().ok())
+        .map(|metadata| metadata.len())
+        .sum()
+}
+
+fn file_size(path: &Path) -> u64 {
+    fs::metadata(path).map(|m| m.len()).unwrap_or(0)
+}
+
+fn modified_at(path: &Path) -> DateTime<Utc> {
+    fs::metadata(path)
+        .and_then(|m| m.modified())
+        .map(DateTime::from)
+        .unwrap_or_else(|_| DateTime::from(std::time::UNIX_EPOCH))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::file::infrastructure::file_operations::StagingArea;
+    use crate::services::shared::infrastructure::clock::testing::{FakeClock, service};
+    use crate::services::vault::domain::models::{IncompleteArtifactKind, QuarantineRecord};
+    use crate::services::vault::infrastructure::persistence::{SnapshotFileKind, SnapshotSource};
+    use std::time::{Duration, SystemTime};
+    use tempfile::TempDir;
+
+    const NOW: &str = "2026-03-01T00:00:00Z";
+
+    fn locations(root: &Path) -> StorageLocations {
+        let locations = StorageLocations {
+            staging: root.join("staging"),
+            quarantine: root.join("quarantine"),
+            snapshots: root.join("snapshots"),
+            cache: root.join("cache"),
+            logs: root.join("logs"),
+            trash: root.join("trash"),
+        };
+        for category in StorageCategory::ALL {
+            fs::create_dir_all(locations.dir(category)).unwrap();
+        }
+        locations
+    }
+
+    fn quotas(bytes: u64) -> StorageQuotas {
+        StorageQuotas {
+            staging: ByteSize(bytes),
+            quarantine: ByteSize(bytes),
+            snapshots: ByteSize(bytes),
+            cache: ByteSize(bytes),
+            logs: ByteSize(bytes),
+            trash: ByteSize(bytes),
+        }
+    }
+
+    /// Write `size` bytes to `path`, last modified `days_ago` days before `NOW`
+    fn write_aged(path: &Path, size: usize, days_ago: u64) {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, vec![0u8; size]).unwrap();
+        let now: DateTime<Utc> = NOW.parse().unwrap();
+        let modified = SystemTime::from(now) - Duration::from_secs(days_ago * 86_400);
+        fs::File::options()
+            .write(true)
+            .open(path)
+            .unwrap()
+            .set_modified(modified)
+            .unwrap();
+    }
+
+    fn quarantine(dir: &Path, name: &str, size: usize, days_ago: i64) {
+        let path = dir.join("Family").join(name);
+        write_aged(&path, size, 0);
+        let record = QuarantineRecord {
+            original_path: name.to_string(),
+            kind: IncompleteArtifactKind::TempFile,
+            reason: IncompleteArtifactKind::TempFile.reason().to_string(),
+            size: size as u64,
+            quarantined_at: NOW.parse::<DateTime<Utc>>().unwrap()
+                - chrono::Duration::days(days_ago),
+        };
+        fs::write(record_path(&path), serde_json::to_vec(&record).unwrap()).unwrap();
+    }
+
+    fn purged(report: &StorageCleanupReport, category: StorageCategory) -> Vec<&str> {
+        report
+            .purged
+            .iter()
+            .filter(|item| item.category == category)
+            .map(|item| item.name.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn test_cleanup_purges_only_eligible_items_oldest_first() {
+        let temp = TempDir::new().unwrap();
+        let locations = locations(temp.path());
+        let clock = FakeClock::at(NOW);
+        let service = StorageQuotaService::with_clock(service(&clock));
+
+        // Staging: a leftover and the area of a running operation
+        write_aged(&locations.staging.join("stage-old/a.txt"), 600, 5);
+        let live = StagingArea::new_in(&locations.staging).unwrap();
+        write_aged(&live.path().join("b.txt"), 600, 9);
+
+        // Quarantine: one old enough, one too recent, one without a record
+        quarantine(&locations.quarantine, "old.age.tmp", 400, 45);
+        quarantine(&locations.quarantine, "recent.age.tmp", 400, 3);
+        write_aged(
+            &locations.quarantine.join("Family/unexplained.bin"),
+            400,
+            90,
+        );
+
+        // Cache: least recently used goes first
+        write_aged(&locations.cache.join("stale.bin"), 400, 10);
+        write_aged(&locations.cache.join("fresh.bin"), 400, 1);
+
+        // Logs: the current log is never purged
+        write_aged(&locations.logs.join(LOG_FILE_NAME), 900, 0);
+        write_aged(&locations.logs.join("barqly-vault.log.1"), 300, 20);
+
+        // Trash: expired and unexpired items
+        write_aged(&locations.trash.join("expired.bin"), 600, 40);
+        write_aged(&locations.trash.join("unexpired.bin"), 600, 2);
+
+        let report = service.enforce_in(&locations, &quotas(500)).unwrap();
+
+        assert_eq!(purged(&report, StorageCategory::Staging), vec!["stage-old"]);
+        assert!(!locations.staging.join("stage-old").exists());
+        assert!(live.path().join("b.txt").exists());
+
+        assert_eq!(
+            purged(&report, StorageCategory::Quarantine),
+            vec![format!("Family{}old.age.tmp", std::path::MAIN_SEPARATOR)]
+        );
+        assert!(!record_path(&locations.quarantine.join("Family/old.age.tmp")).exists());
+        assert!(locations.quarantine.join("Family/recent.age.tmp").exists());
+        assert!(locations.quarantine.join("Family/unexplained.bin").exists());
+
+        assert_eq!(purged(&report, StorageCategory::Cache), vec!["stale.bin"]);
+        assert!(locations.cache.join("fresh.bin").exists());
+
+        assert_eq!(
+            purged(&report, StorageCategory::Logs),
+            vec!["barqly-vault.log.1"]
+        );
+        assert!(locations.logs.join(LOG_FILE_NAME).exists());
+
+        assert_eq!(purged(&report, StorageCategory::Trash), vec!["expired.bin"]);
+        assert!(locations.trash.join("unexpired.bin").exists());
+
+        // Live staging, recent quarantine, the current log and unexpired
+        // trash keep these over quota
+        assert_eq!(
+            report.still_over_quota,
+            vec![
+                StorageCategory::Staging,
+                StorageCategory::Quarantine,
+                StorageCategory::Logs,
+                StorageCategory::Trash
+            ]
+        );
+        let freed: u64 = report.purged.iter().map(|item| item.freed.bytes()).sum();
+        assert_eq!(report.freed, ByteSize(freed));
+    }
+
+    #[test]
+    fn test_snapshot_cleanup_updates_index_and_keeps_newest() {
+        let temp = TempDir::new().unwrap();
+        let locations = locations(temp.path());
+        let service = StorageQuotaService::with_clock(service(&FakeClock::at(NOW)));
+        let store = MetadataSnapshotStore::at(&locations.snapshots);
+        let registry = temp.path().join("registry.json");
+
+        let now: DateTime<Utc> = NOW.parse().unwrap();
+        for (day, contents) in ["one", "two", "three"].iter().enumerate() {
+            fs::write(&registry, contents.repeat(400)).unwrap();
+            let source = SnapshotSource {
+                kind: SnapshotFileKind::Registry,
+                path: registry.clone(),
+            };
+            store
+                .capture("test", &[source], now + chrono::Duration::days(day as i64))
+                .unwrap();
+        }
+        let ids: Vec<String> = store.list().unwrap().into_iter().map(|s| s.id).collect();
+
+        let report = service.enforce_in(&locations, &quotas(1)).unwrap();
+
+        // Oldest first, and the newest restore point survives
+        assert_eq!(
+            purged(&report, StorageCategory::Snapshots),
+            vec![ids[2].as_str(), ids[1].as_str()]
+        );
+        let remaining: Vec<String> = store.list().unwrap().into_iter().map(|s| s.id).collect();
+        assert_eq!(remaining, vec![ids[0].clone()]);
+        assert!(
+            report
+                .still_over_quota
+                .contains(&StorageCategory::Snapshots)
+        );
+    }
+
+    #[test]
+    fn test_usage_report_arithmetic() {
+        let temp = TempDir::new().unwrap();
+        let locations = locations(temp.path());
+        let service = StorageQuotaService::with_clock(service(&FakeClock::at(NOW)));
+
+        write_aged(&locations.cache.join("a.bin"), 100, 1);
+        write_aged(&locations.cache.join("nested/b.bin"), 50, 1);
+        write_aged(&locations.logs.join(LOG_FILE_NAME), 70, 0);
+        write_aged(&locations.logs.join("barqly-vault.log.1"), 30, 5);
+        write_aged(&locations.trash.join("expired.bin"), 20, 31);
+        write_aged(&locations.trash.join("unexpired.bin"), 10, 1);
+
+        let report = service.usage_in(&locations, &quotas(120)).unwrap();
+        let usage = |category| {
+            report
+                .categories
+                .iter()
+                .find(|usage| usage.category == category)
+                .unwrap()
+                .clone()
+        };
+
+        let cache = usage(StorageCategory::Cache);
+        assert_eq!(cache.size, ByteSize(150));
+        assert_eq!(cache.item_count, 2);
+        assert_eq!(cache.purge_eligible, ByteSize(150));
+        assert!(cache.over_quota);
+
+        let logs = usage(StorageCategory::Logs);
+        assert_eq!(logs.size, ByteSize(100));
+        assert_eq!((logs.item_count, logs.eligible_items), (2, 1));
+        assert_eq!(logs.purge_eligible, ByteSize(30));
+        assert!(!logs.over_quota);
+
+        let trash = usage(StorageCategory::Trash);
+        assert_eq!(trash.size, ByteSize(30));
+        assert_eq!(trash.purge_eligible, ByteSize(20));
+
+        assert_eq!(usage(StorageCategory::Staging).size, ByteSize::ZERO);
+        assert_eq!(report.categories.len(), StorageCategory::ALL.len());
+        assert_eq!(report.total, ByteSize(280));
+        assert_eq!(report.total_purge_eligible, ByteSize(200));
+    }
+}
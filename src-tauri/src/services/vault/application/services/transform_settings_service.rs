@@ -0,0 +1,72 @@
+//! Pre-encryption Transform Settings Service
+//!
+//! Stores each vault's transform rules in the local vault settings. Rules
+//! are off until a vault enables them, and a vault whose settings can't be
+//! read is encrypted without transforms rather than failing.
+
+use crate::prelude::*;
+use crate::services::file::infrastructure::file_operations::TransformSettings;
+use crate::services::vault::domain::{VaultError, VaultResult};
+use crate::services::vault::infrastructure::persistence::VaultSettingsRegistry;
+
+/// Service for per-vault pre-encryption transforms
+#[derive(Debug)]
+pub struct TransformSettingsService;
+
+impl TransformSettingsService {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// A vault's transform rules (off if never set)
+    pub fn get_transforms(&self, vault_id: &str) -> VaultResult<TransformSettings> {
+        Ok(load_settings()?.get(vault_id).transforms)
+    }
+
+    /// Replace a vault's transform rules after validating them
+    pub fn update_transforms(
+        &self,
+        vault_id: &str,
+        transforms: TransformSettings,
+    ) -> VaultResult<TransformSettings> {
+        transforms
+            .validate()
+            .map_err(VaultError::InvalidOperation)?;
+
+        let mut settings = load_settings()?;
+        settings.entry(vault_id).transforms = transforms.clone();
+        save_settings(&settings)?;
+
+        info!(
+            vault_id,
+            enabled = transforms.enabled,
+            rule_count = transforms.rules.len(),
+            "Updated vault transforms"
+        );
+        Ok(transforms)
+    }
+
+    /// The rules a new archive of the vault is written with
+    pub fn transforms_for_encryption(&self, vault_id: &str) -> TransformSettings {
+        self.get_transforms(vault_id).unwrap_or_else(|e| {
+            warn!(vault_id, error = %e, "Vault settings unavailable; encrypting without transforms");
+            TransformSettings::default()
+        })
+    }
+}
+
+impl Default for TransformSettingsService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn load_settings() -> VaultResult<VaultSettingsRegistry> {
+    VaultSettingsRegistry::load().map_err(|e| VaultError::StorageError(e.to_string()))
+}
+
+fn save_settings(settings: &VaultSettingsRegistry) -> VaultResult<()> {
+    settings
+        .save()
+        .map_err(|e| VaultError::StorageError(e.to_string()))
+}
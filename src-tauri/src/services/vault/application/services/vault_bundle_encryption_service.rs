@@ -3,16 +3,47 @@
 //! Orchestrates complete vault encryption with manifest-in-bundle architecture.
 //! Proper domain separation: vault operations in vault domain.
 
+use crate::constants::{BYTES_PER_MB, DEFAULT_UPLOAD_PART_SIZE_MB, VERSION};
+use crate::logging::current_trace_id;
 use crate::prelude::*;
+use crate::services::crypto::domain::models::{
+    EncryptionParameters, OperationPhase, PhaseTimer, TimingBreakdown,
+};
 use crate::services::crypto::infrastructure as crypto;
-use crate::services::file::infrastructure::file_operations::FileSelection;
-use crate::services::key_management::shared::{KeyEntry, KeyRegistryService};
-use crate::services::shared::infrastructure::{DeviceInfo, get_vaults_directory};
+use crate::services::file::domain::models::{ParityInfo, ParityOptions, UploadMetadata};
+use crate::services::file::infrastructure::file_operations::{
+    self, FileOpsError, FileSelection, HashAlgorithm, LockedFilePolicy, OverlapExclusions,
+    ProtectedKind, ProtectedPath, ResilientSource, ResilientSourceConfig, ResilientSourceReport,
+    SelectionOverlap, SkippedEntry, SkippedFile, TransformPipeline, TransformSettings,
+    TransformWarning, resolve_selection_overlaps,
+};
+use crate::services::key_management::shared::domain::models::contact::ContactInfo;
+use crate::services::key_management::shared::{
+    ContactService, KeyEntry, KeyRegistry, KeyRegistryService,
+};
+use crate::services::shared::infrastructure::io::{IoPacer, OperationPriority};
+use crate::services::shared::infrastructure::progress::update_global_progress;
+use crate::services::shared::infrastructure::{
+    ClockService, DeviceInfo, get_app_dir, get_config_dir, get_logs_dir, get_vaults_directory,
+    sanitize_vault_name,
+};
 use crate::services::vault;
-use crate::services::vault::application::services::{PayloadStagingService, VaultMetadataService};
+use crate::services::vault::application::services::{
+    ArchiveService, EjectSafetyService, OutputNamingService, PayloadStagingService,
+    VaultConflictService, VaultMetadataService,
+};
 use crate::services::vault::domain::VaultError;
-use crate::services::vault::infrastructure::persistence::metadata::{BundleType, VaultFileEntry};
-use std::path::PathBuf;
+use crate::services::vault::domain::models::{
+    AppCompatibility, AppVersion, CrossVaultNamePolicy, DEFAULT_ARCHIVE_NAME_TEMPLATE,
+    ItemLinkTargets, render_archive_name, shared_bundle_name, validate_archive_comment,
+};
+use crate::services::vault::infrastructure::persistence::metadata::{
+    BundleType, ContactRecipientInfo, VaultDirectoryEntry, VaultFileEntry, VaultMetadata,
+};
+use crate::services::vault::infrastructure::persistence::{AppConfig, ManifestSigner};
+use crate::types::{ProgressDetails, ProgressUpdate};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
 type Result<T> = std::result::Result<T, VaultError>;
 
@@ -23,6 +54,59 @@ pub struct VaultBundleEncryptionInput {
     pub vault_name: String,
     pub file_paths: Vec<String>,
     pub source_root: Option<String>, // Folder name if folder selection, None if files
+    /// How to handle source files that can't be read (locked, no permission)
+    pub locked_file_policy: LockedFilePolicy,
+    /// Read the source with retries and verification (slow or flaky media)
+    pub resilient_source: Option<ResilientSourceConfig>,
+    /// Optional user comment recorded in the archive index and external manifest
+    pub comment: Option<String>,
+    /// I/O priority, switchable while the encryption runs
+    pub io_priority: OperationPriority,
+    /// Write ASCII-armored bundles with a readable preamble (binary when None)
+    pub armored_output: Option<crypto::ArmoredOutputOptions>,
+    /// Write a parity sidecar beside the backup bundle (none when None)
+    pub generate_parity: Option<ParityOptions>,
+    /// Contacts to encrypt to as well as the vault keys
+    pub contact_ids: Vec<String>,
+    /// Refuse a selection overlapping app storage instead of leaving it out
+    pub strict: bool,
+    /// Compression and per-file hash to write with; contacts come from
+    /// `contact_ids`
+    pub parameters: EncryptionParameters,
+    /// The archive being re-encrypted, when migrating its parameters or
+    /// remapping its paths
+    pub migration: Option<MigrationSource>,
+    /// Folder to write the bundles to instead of the vaults directory; they
+    /// are named from the vault's template, checked against the archives
+    /// other vaults have written there
+    pub output_dir: Option<PathBuf>,
+    /// Backup bundle name in the vaults directory instead of the vault's
+    /// own, so the archive under that name is left as it is
+    pub archive_name: Option<String>,
+    /// The vault's pre-encryption transforms; off for migrations, which
+    /// re-archive files that were transformed already
+    pub transforms: TransformSettings,
+}
+
+/// The archive a migration or path remap re-encrypts
+///
+/// Its files are read back from a staging copy in the temp directory.
+#[derive(Debug, Clone)]
+pub struct MigrationSource {
+    pub archive_id: String,
+    pub link: MigrationLink,
+}
+
+/// How the new archive's index entry refers back to the original
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MigrationLink {
+    /// Parameter migration, recorded as `migrated_from`
+    Migrated {
+        /// Mark the original eligible for pruning once the new archive is linked
+        prune_original: bool,
+    },
+    /// Path remap, recorded as `remapped_from`; the original is kept
+    Remapped,
 }
 
 /// Result of vault bundle encryption
@@ -35,6 +119,44 @@ pub struct VaultBundleEncryptionResult {
     pub manifest_path: String,
     pub encryption_revision: u32,
     pub keys_used: Vec<String>,
+    /// Upload checksums for the backup bundle (default part size), if computed
+    pub upload_metadata: Option<UploadMetadata>,
+    /// Source files left out because they couldn't be read
+    pub skipped_entries: Vec<SkippedEntry>,
+    /// False when any selected file was skipped
+    pub complete: bool,
+    /// Archive index entry ID, if the archive was recorded
+    pub archive_id: Option<String>,
+    /// Whether a previous backup bundle at the same path was replaced
+    pub replaced_existing: bool,
+    /// SHA-256 of the replaced backup bundle, for the operation log
+    pub previous_archive_sha256: Option<String>,
+    /// Retries, throughput and verified files, in resilient-source mode
+    pub source_report: Option<ResilientSourceReport>,
+    /// Parity sidecar written beside the backup bundle
+    pub parity: Option<ParityInfo>,
+    /// App storage inside the selection that was left out
+    pub app_data_exclusions: Vec<SelectionOverlap>,
+    /// Files archived untransformed because their transform failed
+    pub transform_warnings: Vec<TransformWarning>,
+    /// Time and bytes per pipeline phase
+    pub timing_breakdown: TimingBreakdown,
+    /// Removable volume the bundles were written to, if any
+    pub volume_name: Option<String>,
+    /// The bundles and their sidecars reached the device (always true off
+    /// removable media)
+    pub safe_to_eject: bool,
+    /// Flushes or read-back checks on removable media that failed or were
+    /// skipped
+    pub eject_warnings: Vec<String>,
+}
+
+/// File and directory entries for a selection
+struct SelectionEntries {
+    files: Vec<VaultFileEntry>,
+    directories: Vec<VaultDirectoryEntry>,
+    skipped: Vec<SkippedFile>,
+    transform_warnings: Vec<TransformWarning>,
 }
 
 /// Vault bundle encryption service
@@ -43,6 +165,10 @@ pub struct VaultBundleEncryptionService {
     metadata_service: VaultMetadataService,
     payload_staging: PayloadStagingService,
     key_registry: KeyRegistryService,
+    archive_service: ArchiveService,
+    output_naming: OutputNamingService,
+    conflict_service: VaultConflictService,
+    eject_safety: EjectSafetyService,
 }
 
 impl VaultBundleEncryptionService {
@@ -51,6 +177,10 @@ pub fn new() -> Self {
             metadata_service: VaultMetadataService::new(),
             payload_staging: PayloadStagingService::new(),
             key_registry: KeyRegistryService::new(),
+            archive_service: ArchiveService::new(),
+            output_naming: OutputNamingService::new(),
+            conflict_service: VaultConflictService::new(),
+            eject_safety: EjectSafetyService::new(),
         }
     }
 
@@ -67,6 +197,15 @@ pub async fn orchestrate_vault_encryption(
             "Starting vault bundle encryption"
         );
 
+        let mut timer = PhaseTimer::start();
+        let phase = timer.begin(OperationPhase::Validation);
+
+        let comment = validate_archive_comment(input.comment.as_deref())?;
+        input
+            .parameters
+            .validate()
+            .map_err(VaultError::InvalidOperation)?;
+
         // Step 1: Load device info
         let device_info = DeviceInfo::load_or_create("2.0.0").map_err(|e| {
             VaultError::OperationFailed(format!("Failed to load device info: {}", e))
@@ -76,6 +215,9 @@ pub async fn orchestrate_vault_encryption(
         let vault = vault::load_vault(&input.vault_id)
             .await
             .map_err(|e| VaultError::NotFound(format!("Vault '{}': {}", input.vault_id, e)))?;
+        // Encrypting records a new manifest and archive entry; refuse before
+        // doing the work if another device's copy is waiting to be resolved
+        self.conflict_service.ensure_resolved(&input.vault_id)?;
 
         if vault.recipients().is_empty() {
             return Err(VaultError::InvalidOperation(
@@ -83,9 +225,64 @@ pub async fn orchestrate_vault_encryption(
             ));
         }
 
+        let contacts = ContactService::new()
+            .resolve(&input.contact_ids)
+            .map_err(|e| VaultError::InvalidOperation(e.to_string()))?;
+
+        // Never archive the app's own storage or the bundles being written
+        let overlap = self.check_selection_overlap(&input)?;
+        timer.end(phase, 0);
+
+        // One reader shared by collection and archiving: resilient for slow or
+        // flaky media, plain otherwise, and paced by the I/O priority
+        let pacer = Arc::new(IoPacer::new(input.io_priority.clone()));
+        let source = Arc::new(
+            ResilientSource::new(
+                input
+                    .resilient_source
+                    .clone()
+                    .unwrap_or_else(ResilientSourceConfig::passthrough),
+            )
+            .with_pacer(pacer),
+        );
+        let resilient = input.resilient_source.is_some();
+
+        // Transformed copies live in the pipeline's staging area until both
+        // bundles are written
+        let mut transforms = TransformPipeline::from_settings(&input.transforms).map_err(|e| {
+            VaultError::OperationFailed(format!("Failed to set up file transforms: {}", e))
+        })?;
+
         // Step 3: Build file entries with hashes (handles folders recursively)
-        let file_entries =
-            self.build_file_entries(&input.file_paths, input.source_root.as_deref())?;
+        let phase = timer.begin(OperationPhase::Hashing);
+        let SelectionEntries {
+            files: file_entries,
+            directories: directory_entries,
+            skipped: skipped_files,
+            transform_warnings,
+        } = self.build_file_entries(
+            &input.file_paths,
+            input.source_root.as_deref(),
+            input.locked_file_policy,
+            Some(&source),
+            &overlap.excluded,
+            input.parameters.hash_algorithm,
+            transforms.as_mut(),
+        )?;
+        let transformed = transforms
+            .as_ref()
+            .map_or(&[][..], |pipeline| pipeline.transformed());
+        let source_bytes: u64 = file_entries.iter().map(|entry| entry.size).sum();
+        timer.end(phase, source_bytes);
+        let excluded_sources: Vec<PathBuf> = skipped_files
+            .iter()
+            .map(|skipped| skipped.source_path.clone())
+            .chain(overlap.excluded.iter().cloned())
+            .collect();
+        let skipped_entries: Vec<SkippedEntry> = skipped_files
+            .into_iter()
+            .map(|skipped| skipped.entry)
+            .collect();
 
         // Step 4: Build or update VaultMetadata
         let mut vault_metadata = self
@@ -97,7 +294,7 @@ pub async fn orchestrate_vault_encryption(
                 &vault.get_key_ids(),
                 &device_info,
                 file_entries,
-                input.source_root,
+                input.source_root.clone(),
             )
             .map_err(|e| VaultError::OperationFailed(format!("Failed to build manifest: {}", e)))?;
 
@@ -110,10 +307,67 @@ pub async fn orchestrate_vault_encryption(
             vault.vault.description.clone(),
             &device_info,
         ) {
+            if let AppCompatibility::TooOld { required } =
+                existing.app_requirements().check(AppVersion::current())
+            {
+                return Err(VaultError::InvalidOperation(format!(
+                    "This vault needs Barqly Vault {} or newer, and this app is {}. \
+                     Update the app before encrypting it again",
+                    required, VERSION
+                )));
+            }
             vault_metadata.versioning.revision = existing.encryption_revision();
+            vault_metadata.inherit_vault_settings(&existing);
+            if input.migration.is_some() {
+                vault_metadata.inherit_transforms(&existing);
+            }
             vault_metadata.increment_version(&device_info);
         }
 
+        if !skipped_entries.is_empty() {
+            warn!(
+                skipped_count = skipped_entries.len(),
+                "Some source files could not be read and were left out of the vault"
+            );
+        }
+        if !transform_warnings.is_empty() {
+            warn!(
+                failed_count = transform_warnings.len(),
+                "Some file transforms failed; those files were archived unchanged"
+            );
+        }
+        vault_metadata.skipped_entries = skipped_entries.clone();
+        vault_metadata.content.directories = directory_entries;
+        vault_metadata.stamp_app_requirements();
+
+        // Record the key the external manifest is signed with; the embedded
+        // copy must not carry a signature of its own
+        vault_metadata.signature = None;
+        match ManifestSigner::from_keys_dir()
+            .and_then(|signer| signer.fingerprint_for(&vault_metadata.vault.id))
+        {
+            Ok(fingerprint) => vault_metadata.signer_fingerprint = Some(fingerprint),
+            Err(e) => {
+                warn!(error = %e, "No manifest signing key; external manifest stays unsigned")
+            }
+        }
+
+        // Items keep their links across backups; flag the ones this backup breaks
+        let targets = ItemLinkTargets::paths_only(
+            vault_metadata.content.files.iter().map(|f| f.path.as_str()),
+        );
+        let dangling_items = vault_metadata
+            .items
+            .iter()
+            .filter(|item| !targets.missing_paths(&item.linked_paths).is_empty())
+            .count();
+        if dangling_items > 0 {
+            warn!(
+                dangling_items,
+                "Vault items link to files that are not in this backup"
+            );
+        }
+
         info!(
             vault = %vault_metadata.label(),
             revision = vault_metadata.versioning.revision,
@@ -121,20 +375,30 @@ pub async fn orchestrate_vault_encryption(
         );
 
         // Step 5: Determine output paths
-        use crate::services::shared::infrastructure::io::SecureTempFile;
-
-        let vaults_dir = get_vaults_directory().map_err(|e| {
-            VaultError::StorageError(format!("Failed to get vaults directory: {}", e))
-        })?;
+        use crate::services::shared::infrastructure::io::{ArchiveOverwrite, SecureTempFile};
 
-        // Check if vault has external recipients (PublicKeyOnly) for dual-output
-        let has_recipients = vault_metadata.has_recipients();
+        let (output_dir, backup_name) = match &input.output_dir {
+            Some(dir) => (
+                dir.clone(),
+                self.external_archive_name(dir, &vault_metadata)?,
+            ),
+            None => {
+                let vaults_dir = get_vaults_directory().map_err(|e| {
+                    VaultError::StorageError(format!("Failed to get vaults directory: {}", e))
+                })?;
+                let name = input
+                    .archive_name
+                    .clone()
+                    .unwrap_or_else(|| format!("{}.age", vault_metadata.vault.sanitized_name));
+                (vaults_dir, name)
+            }
+        };
 
         // Step 6: Create file selection for payload staging
         let file_selection = self.create_file_selection(&input.file_paths)?;
 
         // Step 7: Collect public keys from vault (same for both bundles)
-        let (public_keys, keys_used) = self.collect_vault_public_keys(&vault.get_key_ids())?;
+        let (mut public_keys, keys_used) = self.collect_vault_public_keys(&vault.get_key_ids())?;
 
         if public_keys.is_empty() {
             return Err(VaultError::InvalidOperation(
@@ -142,53 +406,101 @@ pub async fn orchestrate_vault_encryption(
             ));
         }
 
+        // Contacts are recorded apart from the vault keys, before staging so
+        // the embedded manifest carries them too
+        vault_metadata.encryption.contacts = merge_contact_recipients(&mut public_keys, &contacts);
+
+        // External recipients (PublicKeyOnly or contacts) get a shared bundle
+        let has_recipients =
+            vault_metadata.has_recipients() || !vault_metadata.contacts().is_empty();
+
+        // Shamir recovery identity, when configured, can also open the vault
+        if let Some(recovery_key) = self.recovery_share_public_key(&input.vault_id) {
+            public_keys.push(crypto::PublicKey::from(recovery_key));
+        }
+
+        // Bundles are staged and verified, then replace any previous archives
+        // together, so a failure never destroys the last good backup
+        let mut overwrite = ArchiveOverwrite::new();
+
         // Step 8: Create and encrypt BACKUP bundle (full recovery)
-        let backup_encrypted_path =
-            vaults_dir.join(format!("{}.age", vault_metadata.vault.sanitized_name));
+        // Never write over an archive marked immutable
+        self.archive_service.ensure_replaceable(&backup_name)?;
+        let backup_encrypted_path = output_dir.join(&backup_name);
 
         let secure_tar_backup = SecureTempFile::new().map_err(|e| {
             VaultError::OperationFailed(format!("Failed to create secure temp file: {}", e))
         })?;
 
+        let phase = timer.begin(OperationPhase::Archiving);
         self.payload_staging
-            .create_vault_payload(
+            .create_vault_payload_excluding(
                 &file_selection,
                 &vault_metadata,
                 secure_tar_backup.path(),
                 BundleType::Backup,
+                &excluded_sources,
+                Some(&source),
+                input.parameters.compression_level,
+                transformed,
             )
             .map_err(|e| {
                 VaultError::OperationFailed(format!("Failed to create backup payload: {}", e))
             })?;
 
+        // Re-read a sample of the source now that it has been archived; a
+        // mismatch means the reader returned bad data at some point
+        if resilient {
+            let expected: Vec<(String, String)> = vault_metadata
+                .content
+                .files
+                .iter()
+                .map(|entry| {
+                    (
+                        entry.source_path().to_string(),
+                        entry.source_sha256().to_string(),
+                    )
+                })
+                .collect();
+            source.verify_sample(&expected).map_err(|e| {
+                VaultError::OperationFailed(format!("Source verification failed: {}", e))
+            })?;
+        }
+        timer.end(phase, source_bytes);
+
+        let phase = timer.begin(OperationPhase::Encryption);
         let backup_data = std::fs::read(secure_tar_backup.path()).map_err(|e| {
             VaultError::OperationFailed(format!("Failed to read backup archive: {}", e))
         })?;
 
         let backup_encrypted = crypto::encrypt_data_multi_recipient(&backup_data, &public_keys)
             .map_err(|e| VaultError::OperationFailed(format!("Backup encryption failed: {}", e)))?;
+        let backup_encrypted =
+            self.armor_if_requested(backup_encrypted, &input, &vault_metadata)?;
+        timer.end(phase, backup_data.len() as u64);
 
-        std::fs::write(&backup_encrypted_path, backup_encrypted).map_err(|e| {
-            VaultError::OperationFailed(format!("Failed to write backup bundle: {}", e))
-        })?;
+        let phase = timer.begin(OperationPhase::Finalize);
+        overwrite
+            .stage(&backup_encrypted_path, &backup_encrypted)
+            .map_err(|e| {
+                VaultError::OperationFailed(format!("Failed to write backup bundle: {}", e))
+            })?;
 
         info!(
             encrypted_path = %backup_encrypted_path.display(),
             size = backup_data.len(),
-            "Created backup bundle"
+            "Staged backup bundle"
         );
 
         // Securely delete backup temp file
         secure_tar_backup.secure_delete().map_err(|e| {
             VaultError::OperationFailed(format!("Failed to securely delete temp TAR: {}", e))
         })?;
+        timer.end(phase, backup_encrypted.len() as u64);
 
         // Step 9: Create and encrypt SHARED bundle if Recipients present
         let shared_encrypted_path = if has_recipients {
-            let shared_path = vaults_dir.join(format!(
-                "{}-shared.age",
-                vault_metadata.vault.sanitized_name
-            ));
+            let shared_path = output_dir.join(shared_bundle_name(&backup_name));
 
             let secure_tar_shared = SecureTempFile::new().map_err(|e| {
                 VaultError::OperationFailed(format!(
@@ -197,17 +509,24 @@ pub async fn orchestrate_vault_encryption(
                 ))
             })?;
 
+            let phase = timer.begin(OperationPhase::Archiving);
             self.payload_staging
-                .create_vault_payload(
+                .create_vault_payload_excluding(
                     &file_selection,
                     &vault_metadata,
                     secure_tar_shared.path(),
                     BundleType::Shared,
+                    &excluded_sources,
+                    Some(&source),
+                    input.parameters.compression_level,
+                    transformed,
                 )
                 .map_err(|e| {
                     VaultError::OperationFailed(format!("Failed to create shared payload: {}", e))
                 })?;
+            timer.end(phase, source_bytes);
 
+            let phase = timer.begin(OperationPhase::Encryption);
             let shared_data = std::fs::read(secure_tar_shared.path()).map_err(|e| {
                 VaultError::OperationFailed(format!("Failed to read shared archive: {}", e))
             })?;
@@ -216,15 +535,21 @@ pub async fn orchestrate_vault_encryption(
                 .map_err(|e| {
                     VaultError::OperationFailed(format!("Shared encryption failed: {}", e))
                 })?;
+            let shared_encrypted =
+                self.armor_if_requested(shared_encrypted, &input, &vault_metadata)?;
+            timer.end(phase, shared_data.len() as u64);
 
-            std::fs::write(&shared_path, shared_encrypted).map_err(|e| {
-                VaultError::OperationFailed(format!("Failed to write shared bundle: {}", e))
-            })?;
+            let phase = timer.begin(OperationPhase::Finalize);
+            overwrite
+                .stage(&shared_path, &shared_encrypted)
+                .map_err(|e| {
+                    VaultError::OperationFailed(format!("Failed to write shared bundle: {}", e))
+                })?;
 
             info!(
                 shared_path = %shared_path.display(),
                 size = shared_data.len(),
-                "Created shared bundle (stripped for recipients)"
+                "Staged shared bundle (stripped for recipients)"
             );
 
             // Securely delete shared temp file
@@ -234,25 +559,132 @@ pub async fn orchestrate_vault_encryption(
                     e
                 ))
             })?;
+            timer.end(phase, shared_encrypted.len() as u64);
 
             Some(shared_path.to_string_lossy().to_string())
         } else {
             None
         };
 
+        // Replace previous bundles as a set (all or nothing)
+        let phase = timer.begin(OperationPhase::Finalize);
+        let replaced = overwrite.commit().map_err(|e| {
+            VaultError::OperationFailed(format!("Failed to replace vault bundles: {}", e))
+        })?;
+        let previous_archive_sha256 = replaced
+            .iter()
+            .find(|file| file.path == backup_encrypted_path)
+            .and_then(|file| file.previous_sha256.clone());
+
+        info!(
+            encrypted_path = %backup_encrypted_path.display(),
+            replaced_existing = previous_archive_sha256.is_some(),
+            "Wrote vault bundles"
+        );
+
+        // Claim the names in the output folder for this vault (non-fatal if fails)
+        if input.output_dir.is_some() {
+            let mut written = vec![backup_name.clone()];
+            if shared_encrypted_path.is_some() {
+                written.push(shared_bundle_name(&backup_name));
+            }
+            if let Err(e) = self.output_naming.record(
+                &output_dir,
+                &written,
+                &input.vault_id,
+                vault_metadata.label(),
+            ) {
+                warn!("Failed to record output folder claims (non-fatal): {}", e);
+            }
+        }
+
         // Step 10: Write RECOVERY.txt alongside backup .age file (non-fatal if fails)
-        if let Err(e) = self
+        let recovery_file = match self
             .payload_staging
             .write_recovery_file(&vault_metadata, &backup_encrypted_path)
         {
-            warn!("Failed to create RECOVERY.txt (non-fatal): {}", e);
-        }
+            Ok(path) => Some(path),
+            Err(e) => {
+                warn!("Failed to create RECOVERY.txt (non-fatal): {}", e);
+                None
+            }
+        };
+
+        // Step 11: Compute upload metadata for the backup bundle (non-fatal if fails)
+        let upload_metadata = match file_operations::compute_upload_metadata(
+            &backup_encrypted_path,
+            DEFAULT_UPLOAD_PART_SIZE_MB * BYTES_PER_MB,
+        ) {
+            Ok(metadata) => Some(metadata),
+            Err(e) => {
+                warn!("Failed to compute upload metadata (non-fatal): {}", e);
+                None
+            }
+        };
+
+        // Step 12: Write parity beside the backup bundle (non-fatal if fails)
+        let parity = self.write_parity(&backup_encrypted_path, &input);
+
+        // Step 13: On removable media, flush and read back the bundles and
+        // flush their sidecars, so the drive can be pulled once this returns
+        let eject = self.eject_safety.finalize(
+            &replaced,
+            recovery_file.as_deref(),
+            verify_removable_read_back(),
+        );
 
-        // Step 11: Save VaultMetadata to non-sync storage
+        // Step 14: Save VaultMetadata to non-sync storage
+        // The comment is set only now so it stays out of the embedded manifest
+        // and can be edited later without touching the payload
+        vault_metadata.comment = comment;
         self.metadata_service
             .save_manifest(&vault_metadata)
             .map_err(|e| VaultError::StorageError(format!("Failed to save manifest: {}", e)))?;
 
+        // Step 15: Record the archive in the index (non-fatal if fails)
+        let archive_name = backup_encrypted_path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let archive_id = match self.archive_service.record_archive(
+            &vault_metadata,
+            &archive_name,
+            parity.clone(),
+            upload_metadata.as_ref().map(|m| m.sha256_hex.clone()),
+            public_keys
+                .iter()
+                .map(|key| key.as_str().to_string())
+                .collect(),
+        ) {
+            Ok(entry) => Some(entry.archive_id),
+            Err(e) => {
+                warn!("Failed to record archive in index (non-fatal): {}", e);
+                None
+            }
+        };
+        if let (Some(archive_id), Some(migration)) = (&archive_id, &input.migration) {
+            let linked = match migration.link {
+                MigrationLink::Migrated { prune_original } => self.archive_service.link_migration(
+                    &input.vault_id,
+                    archive_id,
+                    &migration.archive_id,
+                    prune_original,
+                ),
+                MigrationLink::Remapped => self.archive_service.link_remap(
+                    &input.vault_id,
+                    archive_id,
+                    &migration.archive_id,
+                ),
+            };
+            if let Err(e) = linked {
+                warn!("Failed to link re-encrypted archive (non-fatal): {}", e);
+            }
+        }
+        timer.end(phase, 0);
+
+        let timing_breakdown = timer.finish();
+        timing_breakdown.log("encryption");
+
         info!(
             vault = %vault_metadata.label(),
             revision = vault_metadata.versioning.revision,
@@ -270,17 +702,213 @@ pub async fn orchestrate_vault_encryption(
             ),
             encryption_revision: vault_metadata.encryption_revision(),
             keys_used,
+            upload_metadata,
+            complete: skipped_entries.is_empty(),
+            skipped_entries,
+            archive_id,
+            replaced_existing: previous_archive_sha256.is_some(),
+            previous_archive_sha256,
+            source_report: resilient.then(|| source.report()),
+            parity,
+            app_data_exclusions: overlap.overlaps,
+            transform_warnings,
+            timing_breakdown,
+            volume_name: eject.volume_name,
+            safe_to_eject: eject.safe_to_eject,
+            eject_warnings: eject.warnings,
+        })
+    }
+
+    /// Backup bundle name in an external output folder
+    ///
+    /// Rendered from the vault's archive name template for today, then
+    /// checked against the archives other vaults have written to the folder.
+    fn external_archive_name(
+        &self,
+        output_dir: &Path,
+        vault_metadata: &VaultMetadata,
+    ) -> Result<String> {
+        let slug = &vault_metadata.vault.sanitized_name;
+        let template = vault_metadata
+            .template
+            .as_ref()
+            .map_or(DEFAULT_ARCHIVE_NAME_TEMPLATE, |template| {
+                template.archive_name_template.as_str()
+            });
+        let today = ClockService::global()
+            .now()
+            .with_timezone(&chrono::Local)
+            .date_naive();
+        let policy = match AppConfig::load() {
+            Ok(config) => config.cross_vault_name_policy,
+            Err(e) => {
+                warn!(error = %e, "Failed to load app config, using default naming policy");
+                CrossVaultNamePolicy::default()
+            }
+        };
+
+        self.output_naming.resolve_name(
+            output_dir,
+            &render_archive_name(template, slug, today),
+            vault_metadata.vault_id(),
+            slug,
+            policy,
+        )
+    }
+
+    /// Write parity beside a finished backup bundle, if the input asks for it
+    ///
+    /// Parity left from the archive this one replaced is removed first, so a
+    /// sidecar never describes a different archive. Failures are logged; the
+    /// archive itself is already complete.
+    fn write_parity(
+        &self,
+        archive_path: &Path,
+        input: &VaultBundleEncryptionInput,
+    ) -> Option<ParityInfo> {
+        let previous = file_operations::generate_parity_path(archive_path);
+        match std::fs::remove_file(&previous) {
+            Ok(()) => debug!(path = %previous.display(), "Removed parity of replaced archive"),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => warn!(path = %previous.display(), error = %e, "Failed to remove old parity"),
+        }
+        let options = input.generate_parity?;
+
+        let operation_id = format!("parity_{}", input.vault_id);
+        let report = |bytes_processed: u64, total_bytes: u64| {
+            update_global_progress(
+                &operation_id,
+                ProgressUpdate {
+                    operation_id: operation_id.clone(),
+                    progress: bytes_processed as f32 / total_bytes.max(1) as f32,
+                    message: "Generating parity...".to_string(),
+                    details: Some(ProgressDetails::Parity {
+                        bytes_processed: bytes_processed.into(),
+                        total_bytes: total_bytes.into(),
+                    }),
+                    timestamp: chrono::Utc::now(),
+                    estimated_time_remaining: None,
+                    io_priority: input.io_priority.get(),
+                    trace_id: current_trace_id(),
+                },
+            )
+        };
+        match file_operations::generate_parity(archive_path, options.redundancy_percent, &report) {
+            Ok(parity) => Some(parity),
+            Err(e) => {
+                warn!("Failed to write parity sidecar (non-fatal): {}", e);
+                None
+            }
+        }
+    }
+
+    /// Armor an encrypted bundle and add its preamble, if the input asks for it
+    ///
+    /// The vault name only goes into the preamble when the user opted in.
+    fn armor_if_requested(
+        &self,
+        encrypted: Vec<u8>,
+        input: &VaultBundleEncryptionInput,
+        vault_metadata: &VaultMetadata,
+    ) -> Result<Vec<u8>> {
+        let Some(options) = &input.armored_output else {
+            return Ok(encrypted);
+        };
+        let preamble = crypto::ArchivePreamble::new(
+            chrono::Utc::now(),
+            &vault_metadata.schema,
+            options
+                .include_vault_name
+                .then_some(input.vault_name.as_str()),
+        );
+        crypto::armor_archive(&encrypted, &preamble)
+            .map_err(|e| VaultError::OperationFailed(format!("Failed to armor bundle: {}", e)))
+    }
+
+    /// Find app storage the selection contains or is inside
+    ///
+    /// Checks the app data, config and log directories, vault storage, the
+    /// temp directory staging is built in, and the bundles this run writes.
+    /// Overlaps are left out, or refused when `input.strict` is set.
+    fn check_selection_overlap(
+        &self,
+        input: &VaultBundleEncryptionInput,
+    ) -> Result<OverlapExclusions> {
+        let mut protected = Vec::new();
+        for (kind, dir) in [
+            (ProtectedKind::AppData, get_app_dir()),
+            (ProtectedKind::AppData, get_config_dir()),
+            (ProtectedKind::AppData, get_logs_dir()),
+            (ProtectedKind::VaultStorage, get_vaults_directory()),
+        ] {
+            match dir {
+                Ok(path) => protected.push(ProtectedPath::new(kind, path)),
+                Err(e) => warn!(error = %e, "Could not resolve app storage for overlap check"),
+            }
+        }
+        // A migration reads its files back from a staging copy there
+        if input.migration.is_none() {
+            protected.push(ProtectedPath::new(
+                ProtectedKind::Staging,
+                std::env::temp_dir(),
+            ));
+        }
+        // Names in an output folder aren't settled yet, so keep out all of it
+        if let Some(output_dir) = &input.output_dir {
+            protected.push(ProtectedPath::new(
+                ProtectedKind::Output,
+                output_dir.clone(),
+            ));
+        } else if let Ok(vaults_dir) = get_vaults_directory()
+            && let Ok(name) = sanitize_vault_name(&input.vault_name)
+        {
+            let backup_name = input
+                .archive_name
+                .clone()
+                .unwrap_or_else(|| format!("{}.age", name.sanitized));
+            for file_name in [shared_bundle_name(&backup_name), backup_name] {
+                protected.push(ProtectedPath::new(
+                    ProtectedKind::Output,
+                    vaults_dir.join(file_name),
+                ));
+            }
+        }
+
+        let selected: Vec<PathBuf> = input.file_paths.iter().map(PathBuf::from).collect();
+        resolve_selection_overlaps(&selected, &protected, input.strict).map_err(|e| match e {
+            FileOpsError::SelectionOverlapsAppData { paths } => {
+                VaultError::SelectionOverlapsAppData {
+                    paths: paths
+                        .iter()
+                        .map(|path| path.display().to_string())
+                        .collect(),
+                }
+            }
+            e => VaultError::OperationFailed(format!("Failed to check selection: {}", e)),
         })
     }
 
     /// Build file entries with SHA256 hashes (handles files and folders)
+    ///
+    /// Also returns the folder's directories and the files skipped under
+    /// `locked_file_policy`. Reads through `resilient_source` when given, and
+    /// leaves out `excluded` paths and everything below them. Files are
+    /// hashed again for `hash_algorithm` when it isn't SHA-256. Files a
+    /// `transforms` rule matches are described as transformed, and the
+    /// transforms that failed are returned as warnings.
+    #[allow(clippy::too_many_arguments)]
     fn build_file_entries(
         &self,
         file_paths: &[String],
         source_root: Option<&str>,
-    ) -> Result<Vec<VaultFileEntry>> {
+        locked_file_policy: LockedFilePolicy,
+        resilient_source: Option<&ResilientSource>,
+        excluded: &[PathBuf],
+        hash_algorithm: HashAlgorithm,
+        mut transforms: Option<&mut TransformPipeline>,
+    ) -> Result<SelectionEntries> {
         use crate::services::file::infrastructure::file_operations::{
-            SelectionType as FileSelectionType, collect_files_with_metadata,
+            SelectionType as FileSelectionType, collect_files_resilient, collect_files_with_policy,
         };
 
         // Infer selection type from source_root presence
@@ -291,22 +919,91 @@ fn build_file_entries(
         };
 
         // Use reusable file collection utility
-        let collected_files =
-            collect_files_with_metadata(file_paths, file_selection_type, source_root).map_err(
-                |e| VaultError::OperationFailed(format!("Failed to collect files: {}", e)),
-            )?;
+        let collection = match resilient_source {
+            Some(source) => collect_files_resilient(
+                file_paths,
+                file_selection_type,
+                source_root,
+                locked_file_policy,
+                source,
+                excluded,
+            ),
+            None => collect_files_with_policy(
+                file_paths,
+                file_selection_type,
+                source_root,
+                locked_file_policy,
+                excluded,
+            ),
+        }
+        .map_err(|e| VaultError::OperationFailed(format!("Failed to collect files: {}", e)))?;
+
+        if let Some(pipeline) = transforms.as_deref_mut() {
+            pipeline.reserve(collection.files.iter().map(|cf| cf.relative_path.as_str()));
+        }
 
         // Convert to VaultFileEntry
-        let entries = collected_files
-            .into_iter()
-            .map(|cf| VaultFileEntry {
+        let mut entries = Vec::with_capacity(collection.files.len());
+        let mut transform_warnings = Vec::new();
+        for cf in collection.files {
+            let mut entry = VaultFileEntry {
                 path: cf.relative_path,
+                raw_path: cf.raw_path,
+                lossy_name: cf.lossy_name,
                 size: cf.size,
                 sha256: cf.sha256,
+                hash_algorithm: HashAlgorithm::Sha256,
+                digest: None,
+                ownership: cf.ownership,
+                content_type: cf.content_type,
+                content_type_mismatch: cf.content_type_mismatch,
+                transform: None,
+            };
+
+            // A name that isn't valid Unicode can't be renamed faithfully,
+            // so those files are archived as they are
+            let transformed = match transforms.as_deref_mut() {
+                Some(pipeline) if !entry.lossy_name => {
+                    pipeline.apply(&cf.source_path, &entry.path, entry.content_type.as_deref())
+                }
+                _ => None,
+            };
+            let archived_path = match transformed {
+                Some(Ok(file)) => {
+                    entry.record_transform(&file);
+                    file.staged_path
+                }
+                Some(Err(warning)) => {
+                    transform_warnings.push(warning);
+                    cf.source_path
+                }
+                None => cf.source_path,
+            };
+
+            entry.rehash(&archived_path, hash_algorithm).map_err(|e| {
+                VaultError::OperationFailed(format!("Failed to hash '{}': {}", entry.path, e))
+            })?;
+            entries.push(entry);
+        }
+
+        let directories = collection
+            .directories
+            .into_iter()
+            .map(|cd| VaultDirectoryEntry {
+                path: cd.relative_path,
+                raw_path: cd.raw_path,
+                lossy_name: cd.lossy_name,
+                modified: cd.modified,
+                permissions: cd.permissions,
             })
             .collect();
 
-        Ok(entries)
+        Ok(SelectionEntries {
+            files: entries,
+            directories,
+            skipped: collection.skipped,
+            transform_warnings,
+        })
     }
 
     /// Create FileSelection from paths
@@ -343,6 +1040,12 @@ fn collect_vault_public_keys(
                     public_keys.push(crypto::PublicKey::from(public_key.clone()));
                     keys_used.push(label.clone());
                 }
+                Ok(KeyEntry::YubikeyPending { .. }) => {
+                    warn!(
+                        key_id,
+                        "Reserved YubiKey slot has no recipient yet, skipping"
+                    );
+                }
                 Err(_) => {
                     warn!(key_id, "Key not found in registry, skipping");
                 }
@@ -351,6 +1054,60 @@ fn collect_vault_public_keys(
 
         Ok((public_keys, keys_used))
     }
+
+    /// Public key of the vault's recovery share identity, if one was created
+    fn recovery_share_public_key(&self, vault_id: &str) -> Option<String> {
+        match KeyRegistry::load() {
+            Ok(registry) => registry
+                .recovery_shares
+                .get(vault_id)
+                .map(|config| config.public_key.clone()),
+            Err(e) => {
+                warn!(vault_id, error = %e, "Failed to load registry for recovery shares");
+                None
+            }
+        }
+    }
+}
+
+/// Whether bundles on removable media are read back after writing; on
+/// unless turned off in the app config
+fn verify_removable_read_back() -> bool {
+    match AppConfig::load() {
+        Ok(config) => !config.skip_removable_read_back,
+        Err(e) => {
+            warn!(error = %e, "Failed to load app config, reading bundles back");
+            true
+        }
+    }
+}
+
+/// Add contacts to the encryption recipients, returning their manifest entries
+///
+/// A contact whose key is already a recipient, or that was chosen twice,
+/// adds nothing and isn't recorded again.
+fn merge_contact_recipients(
+    public_keys: &mut Vec<crypto::PublicKey>,
+    contacts: &[ContactInfo],
+) -> Vec<ContactRecipientInfo> {
+    let mut recorded = Vec::new();
+    for contact in contacts {
+        if public_keys
+            .iter()
+            .any(|key| key.as_str() == contact.recipient)
+        {
+            warn!(contact_id = %contact.id, "Contact is already a recipient, skipping");
+            continue;
+        }
+        public_keys.push(crypto::PublicKey::from(contact.recipient.clone()));
+        recorded.push(ContactRecipientInfo {
+            contact_id: contact.id.clone(),
+            name: contact.name.clone(),
+            public_key: contact.recipient.clone(),
+            fingerprint: contact.fingerprint.clone(),
+        });
+    }
+    recorded
 }
 
 impl Default for VaultBundleEncryptionService {
@@ -367,4 +1124,36 @@ mod tests {
     fn test_vault_bundle_encryption_service_creation() {
         let _service = VaultBundleEncryptionService::new();
     }
+
+    fn contact(id: &str, name: &str, recipient: &str) -> ContactInfo {
+        ContactInfo {
+            id: id.to_string(),
+            name: name.to_string(),
+            recipient: recipient.to_string(),
+            fingerprint: "AAAA-BBBB-CCCC-DDDD".to_string(),
+            note: None,
+            added_at: chrono::Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_merge_contact_recipients() {
+        let mut public_keys = vec![
+            crypto::PublicKey::from("age1vaultkey1".to_string()),
+            crypto::PublicKey::from("age1vaultkey2".to_string()),
+        ];
+        let contacts = vec![
+            contact("contact-sam-1", "Sam", "age1sam"),
+            contact("contact-sam-1", "Sam", "age1sam"),
+            contact("contact-old-1", "Old", "age1vaultkey2"),
+        ];
+
+        let recorded = merge_contact_recipients(&mut public_keys, &contacts);
+
+        let keys: Vec<&str> = public_keys.iter().map(|key| key.as_str()).collect();
+        assert_eq!(keys, vec!["age1vaultkey1", "age1vaultkey2", "age1sam"]);
+        assert_eq!(recorded.len(), 1);
+        assert_eq!(recorded[0].contact_id, "contact-sam-1");
+        assert_eq!(recorded[0].name, "Sam");
+    }
 }
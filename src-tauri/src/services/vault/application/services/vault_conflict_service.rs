@@ -0,0 +1,916 @@
+//! Vault Conflict Service
+//!
+//! Notices when the same vault was changed on two devices and a sync service
+//! kept one device's files over the other's, or kept both side by side, and
+//! settles it on the user's word. Until then every write to the vault's
+//! manifest or index entries is refused (see `vault_generation`).
+//!
+//! Resolving writes the chosen manifest and index entries one generation
+//! above every copy, as this device, and removes the vault from the
+//! conflicting copies so the sync service stops offering them.
+
+use crate::prelude::*;
+use crate::services::file::infrastructure::file_operations::{
+    HashAlgorithm, calculate_file_hash_with,
+};
+use crate::services::shared::infrastructure::io::atomic_write_sync;
+use crate::services::shared::infrastructure::path_management::{
+    STORE_MANIFESTS_DIR, get_shared_store_dir, get_vaults_directory, get_vaults_manifest_dir,
+};
+use crate::services::vault::domain::models::{
+    ArchiveIndexEntry, ConflictEvidence, ConflictResolution, ConflictStrategy, GenerationFile,
+    GenerationStamp, VaultConflict, VaultSummary, VaultSyncStatus,
+};
+use crate::services::vault::domain::{VaultError, VaultResult};
+use crate::services::vault::infrastructure::persistence::{
+    ArchiveIndex, GenerationLedger, VaultConflictError, VaultMetadata, conflicted_copies,
+    is_conflicted_copy, signed_manifest_json,
+};
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// A directory of vault manifests and the archive index beside it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VaultFileSet {
+    pub manifest_dir: PathBuf,
+    pub index_path: PathBuf,
+}
+
+/// Where this profile's vault files live, and the device writing them
+#[derive(Debug, Clone)]
+pub struct ConflictLocations {
+    pub ledger: GenerationLedger,
+    /// This profile's own files first, then the shared store's
+    pub file_sets: Vec<VaultFileSet>,
+    /// Where archives are written, to check entries when merging
+    pub vaults_dir: PathBuf,
+}
+
+impl ConflictLocations {
+    /// The standard locations
+    pub fn resolve() -> VaultResult<Self> {
+        let storage_error = |e: &dyn std::fmt::Display| VaultError::StorageError(e.to_string());
+        let mut file_sets = vec![VaultFileSet {
+            manifest_dir: get_vaults_manifest_dir().map_err(|e| storage_error(&e))?,
+            index_path: ArchiveIndex::get_index_path().map_err(|e| storage_error(&e))?,
+        }];
+        if let Some(store_dir) = get_shared_store_dir().map_err(|e| storage_error(&e))? {
+            file_sets.push(VaultFileSet {
+                manifest_dir: store_dir.join(STORE_MANIFESTS_DIR),
+                index_path: ArchiveIndex::store_index_path(&store_dir),
+            });
+        }
+
+        Ok(Self {
+            ledger: GenerationLedger::load().map_err(|e| storage_error(&e))?,
+            file_sets,
+            vaults_dir: get_vaults_directory().map_err(|e| storage_error(&e))?,
+        })
+    }
+}
+
+/// A manifest file on disk
+#[derive(Debug, Clone)]
+struct ManifestFile {
+    path: PathBuf,
+    metadata: VaultMetadata,
+    /// A sync service's conflicting copy rather than the file itself
+    copy: bool,
+}
+
+/// A vault's entries in one archive index file
+#[derive(Debug, Clone)]
+struct IndexFile {
+    path: PathBuf,
+    entries: Vec<ArchiveIndexEntry>,
+    stamp: GenerationStamp,
+    copy: bool,
+}
+
+/// Service for vaults changed on two devices at once
+#[derive(Debug, Default)]
+pub struct VaultConflictService;
+
+impl VaultConflictService {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Vaults suspected to be in conflict, in the standard locations
+    pub fn conflicts(&self) -> VaultResult<Vec<VaultConflict>> {
+        Ok(self.conflicts_in(&ConflictLocations::resolve()?))
+    }
+
+    /// Vaults suspected to be in conflict, by vault ID
+    pub fn conflicts_in(&self, locations: &ConflictLocations) -> Vec<VaultConflict> {
+        let ledger = &locations.ledger;
+        let mut evidence: BTreeMap<String, Vec<ConflictEvidence>> = BTreeMap::new();
+
+        for set in &locations.file_sets {
+            for file in manifest_files(&set.manifest_dir) {
+                let vault_id = file.metadata.vault_id().to_string();
+                let found = if file.copy {
+                    Some(ConflictEvidence::ConflictedCopy {
+                        file: GenerationFile::Manifest,
+                        path: file.path.display().to_string(),
+                    })
+                } else {
+                    ledger.hidden_write(
+                        &vault_id,
+                        GenerationFile::Manifest,
+                        &file.metadata.generation_stamp,
+                    )
+                };
+                evidence.entry(vault_id).or_default().extend(found);
+            }
+
+            let index = load_index(&set.index_path);
+            for vault_id in known_vaults(&index) {
+                let found = ledger.hidden_write(
+                    &vault_id,
+                    GenerationFile::ArchiveIndex,
+                    &index.generation(&vault_id),
+                );
+                evidence.entry(vault_id).or_default().extend(found);
+            }
+            for copy_path in conflicted_copies(&set.index_path) {
+                let copy = load_index(&copy_path);
+                for vault_id in known_vaults(&copy) {
+                    if copy.diverges_from(&index, &vault_id) {
+                        evidence.entry(vault_id).or_default().push(
+                            ConflictEvidence::ConflictedCopy {
+                                file: GenerationFile::ArchiveIndex,
+                                path: copy_path.display().to_string(),
+                            },
+                        );
+                    }
+                }
+            }
+        }
+
+        evidence
+            .into_iter()
+            .filter(|(_, evidence)| !evidence.is_empty())
+            .map(|(vault_id, evidence)| VaultConflict { vault_id, evidence })
+            .collect()
+    }
+
+    /// Fill in each vault's sync status
+    ///
+    /// Vault files that can't be checked leave the list as it is rather than
+    /// failing it.
+    pub fn apply(&self, vaults: &mut [VaultSummary]) {
+        let conflicted: HashSet<String> = match self.conflicts() {
+            Ok(conflicts) => conflicts.into_iter().map(|c| c.vault_id).collect(),
+            Err(e) => {
+                warn!(error = %e, "Failed to check vaults for conflicts");
+                return;
+            }
+        };
+
+        for vault in vaults.iter_mut() {
+            vault.sync_status = if conflicted.contains(&vault.id) {
+                VaultSyncStatus::ConflictSuspected
+            } else {
+                VaultSyncStatus::InSync
+            };
+        }
+    }
+
+    /// Fail with `ConflictSuspected` if the vault is in conflict
+    pub fn ensure_resolved(&self, vault_id: &str) -> VaultResult<()> {
+        self.ensure_resolved_in(&ConflictLocations::resolve()?, vault_id)
+    }
+
+    pub fn ensure_resolved_in(
+        &self,
+        locations: &ConflictLocations,
+        vault_id: &str,
+    ) -> VaultResult<()> {
+        match self
+            .conflicts_in(locations)
+            .into_iter()
+            .find(|conflict| conflict.vault_id == vault_id)
+            .and_then(|conflict| conflict.evidence.into_iter().next())
+        {
+            Some(evidence) => Err(VaultConflictError {
+                vault_id: vault_id.to_string(),
+                evidence,
+            }
+            .into()),
+            None => Ok(()),
+        }
+    }
+
+    /// Settle a vault's conflict in the standard locations
+    pub fn resolve(
+        &self,
+        vault_id: &str,
+        strategy: ConflictStrategy,
+    ) -> VaultResult<ConflictResolution> {
+        self.resolve_in(&ConflictLocations::resolve()?, vault_id, strategy)
+    }
+
+    /// Keep one side's manifest and index entries, or merge the entries
+    ///
+    /// `PreferLocal` keeps the newest copies this device wrote, and
+    /// `PreferRemote` the newest another device wrote, falling back to the
+    /// newest copy of a file the preferred side never wrote. `MergeIndexes`
+    /// keeps the newest manifest and every entry all copies agree on, plus
+    /// the other entries whose archive file is still there and unchanged.
+    pub fn resolve_in(
+        &self,
+        locations: &ConflictLocations,
+        vault_id: &str,
+        strategy: ConflictStrategy,
+    ) -> VaultResult<ConflictResolution> {
+        if !self
+            .conflicts_in(locations)
+            .iter()
+            .any(|conflict| conflict.vault_id == vault_id)
+        {
+            return Err(VaultError::InvalidOperation(format!(
+                "Vault '{vault_id}' isn't in conflict"
+            )));
+        }
+
+        let ledger = &locations.ledger;
+        let manifests = vault_manifests(locations, vault_id);
+        let indexes = vault_indexes(locations, vault_id);
+        if manifests.is_empty() {
+            return Err(VaultError::NotFound(vault_id.to_string()));
+        }
+
+        let preferred = |stamp: &GenerationStamp| match strategy {
+            ConflictStrategy::PreferLocal => stamp.written_by(ledger.device_id()),
+            ConflictStrategy::PreferRemote => !stamp.written_by(ledger.device_id()),
+            ConflictStrategy::MergeIndexes => true,
+        };
+        if !manifests
+            .iter()
+            .any(|m| preferred(&m.metadata.generation_stamp))
+            && !indexes.iter().any(|i| preferred(&i.stamp))
+        {
+            return Err(VaultError::InvalidOperation(match strategy {
+                ConflictStrategy::PreferLocal => {
+                    "No copy of this vault was written on this device".to_string()
+                }
+                _ => "No copy of this vault was written on another device".to_string(),
+            }));
+        }
+
+        // Manifest: the preferred side's newest, else the newest
+        let chosen = newest(
+            manifests
+                .iter()
+                .filter(|m| preferred(&m.metadata.generation_stamp)),
+            |m| &m.metadata.generation_stamp,
+        )
+        .or_else(|| newest(manifests.iter(), |m| &m.metadata.generation_stamp))
+        .expect("at least one manifest");
+        let mut metadata = chosen.metadata.clone();
+        metadata.generation_stamp = ledger.stamp_over(
+            vault_id,
+            GenerationFile::Manifest,
+            &highest(manifests.iter().map(|m| &m.metadata.generation_stamp)),
+        );
+
+        // Index entries
+        let mut dropped_archives = Vec::new();
+        let entries = if strategy == ConflictStrategy::MergeIndexes {
+            let (entries, dropped) = merge_entries(&indexes, &locations.vaults_dir);
+            dropped_archives = dropped;
+            entries
+        } else {
+            newest(indexes.iter().filter(|i| preferred(&i.stamp)), |i| &i.stamp)
+                .or_else(|| newest(indexes.iter(), |i| &i.stamp))
+                .map(|i| i.entries.clone())
+                .unwrap_or_default()
+        };
+        let index_stamp = ledger.stamp_over(
+            vault_id,
+            GenerationFile::ArchiveIndex,
+            &highest(indexes.iter().map(|i| &i.stamp)),
+        );
+
+        let removed_copies = self.write_resolution(
+            locations,
+            &manifests,
+            &indexes,
+            &metadata,
+            entries.clone(),
+            &index_stamp,
+        )?;
+
+        info!(
+            vault_id,
+            ?strategy,
+            manifest_generation = metadata.generation_stamp.generation,
+            archive_count = entries.len(),
+            dropped = dropped_archives.len(),
+            "Resolved vault conflict"
+        );
+        Ok(ConflictResolution {
+            vault_id: vault_id.to_string(),
+            strategy,
+            manifest_generation: metadata.generation_stamp.generation,
+            archive_index_generation: index_stamp.generation,
+            archive_count: entries.len(),
+            dropped_archives,
+            removed_copies,
+        })
+    }
+
+    /// Write the resolved files and clear the vault out of the copies,
+    /// returning the copies changed
+    fn write_resolution(
+        &self,
+        locations: &ConflictLocations,
+        manifests: &[ManifestFile],
+        indexes: &[IndexFile],
+        metadata: &VaultMetadata,
+        entries: Vec<ArchiveIndexEntry>,
+        index_stamp: &GenerationStamp,
+    ) -> VaultResult<Vec<String>> {
+        let vault_id = metadata.vault_id();
+        let write_error = |e: Box<dyn std::error::Error + Send + Sync>| {
+            VaultError::StorageError(format!("Failed to write resolved vault: {e}"))
+        };
+
+        // Every manifest of the vault gets the resolved one; with only
+        // copies left, it goes back under the vault's own name
+        let mut manifest_paths: Vec<PathBuf> = manifests
+            .iter()
+            .filter(|m| !m.copy)
+            .map(|m| m.path.clone())
+            .collect();
+        if manifest_paths.is_empty() {
+            let dir = manifests[0].path.parent().unwrap_or(Path::new("."));
+            manifest_paths.push(dir.join(format!("{}.manifest", metadata.vault.sanitized_name)));
+        }
+        let json =
+            signed_manifest_json(metadata).map_err(|e| VaultError::StorageError(e.to_string()))?;
+        for path in &manifest_paths {
+            atomic_write_sync(path, json.as_bytes()).map_err(write_error)?;
+        }
+        locations
+            .ledger
+            .record(
+                vault_id,
+                GenerationFile::Manifest,
+                metadata.generation_stamp.generation,
+            )
+            .map_err(write_error)?;
+
+        // The entries go to the index `ArchiveIndex::save` keeps the vault
+        // in: the shared store's when its manifest is there
+        let home = locations
+            .file_sets
+            .iter()
+            .rev()
+            .find(|set| {
+                manifest_paths
+                    .iter()
+                    .any(|p| p.parent() == Some(&set.manifest_dir))
+            })
+            .or_else(|| locations.file_sets.first())
+            .map(|set| set.index_path.clone())
+            .ok_or_else(|| VaultError::StorageError("No archive index location".to_string()))?;
+        for set in &locations.file_sets {
+            let mut index = load_index(&set.index_path);
+            if set.index_path == home {
+                index.vaults.insert(vault_id.to_string(), entries.clone());
+                index
+                    .generations
+                    .insert(vault_id.to_string(), index_stamp.clone());
+            } else if !forget_vault(&mut index, vault_id) {
+                continue;
+            }
+            index.save_to(&set.index_path).map_err(write_error)?;
+        }
+        locations
+            .ledger
+            .record(
+                vault_id,
+                GenerationFile::ArchiveIndex,
+                index_stamp.generation,
+            )
+            .map_err(write_error)?;
+
+        let mut removed_copies = Vec::new();
+        for copy in manifests.iter().filter(|m| m.copy) {
+            fs::remove_file(&copy.path).map_err(|e| write_error(e.into()))?;
+            removed_copies.push(copy.path.display().to_string());
+        }
+        for copy in indexes.iter().filter(|i| i.copy) {
+            let mut index = load_index(&copy.path);
+            forget_vault(&mut index, vault_id);
+            if index.vaults.is_empty() && index.generations.is_empty() {
+                fs::remove_file(&copy.path).map_err(|e| write_error(e.into()))?;
+            } else {
+                index.save_to(&copy.path).map_err(write_error)?;
+            }
+            removed_copies.push(copy.path.display().to_string());
+        }
+        Ok(removed_copies)
+    }
+}
+
+/// Every readable manifest in `dir`, marking sync services' copies
+fn manifest_files(dir: &Path) -> Vec<ManifestFile> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    let mut files: Vec<ManifestFile> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            let extension = path.extension().and_then(|s| s.to_str());
+            let stem = path
+                .file_stem()
+                .and_then(|s| s.to_str())
+                .unwrap_or_default();
+            matches!(extension, Some("manifest" | "json"))
+                && !stem.ends_with(".tmp")
+                && !stem.ends_with(".bak")
+        })
+        .filter_map(|path| {
+            let content = fs::read_to_string(&path).ok()?;
+            let metadata = serde_json::from_str::<VaultMetadata>(&content).ok()?;
+            let copy = path
+                .file_stem()
+                .and_then(|s| s.to_str())
+                .is_some_and(|stem| is_conflicted_copy(stem, &metadata.vault.sanitized_name));
+            Some(ManifestFile {
+                path,
+                metadata,
+                copy,
+            })
+        })
+        .collect();
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+    files
+}
+
+/// The vault's manifests and their copies in every location
+fn vault_manifests(locations: &ConflictLocations, vault_id: &str) -> Vec<ManifestFile> {
+    locations
+        .file_sets
+        .iter()
+        .flat_map(|set| manifest_files(&set.manifest_dir))
+        .filter(|file| file.metadata.vault_id() == vault_id)
+        .collect()
+}
+
+/// The vault's entries in every index and index copy that knows it
+fn vault_indexes(locations: &ConflictLocations, vault_id: &str) -> Vec<IndexFile> {
+    let mut files = Vec::new();
+    for set in &locations.file_sets {
+        let copies = conflicted_copies(&set.index_path)
+            .into_iter()
+            .map(|path| (path, true));
+        for (path, copy) in std::iter::once((set.index_path.clone(), false)).chain(copies) {
+            let index = load_index(&path);
+            if known_vaults(&index).contains(vault_id) {
+                files.push(IndexFile {
+                    entries: index.entries(vault_id).to_vec(),
+                    stamp: index.generation(vault_id),
+                    path,
+                    copy,
+                });
+            }
+        }
+    }
+    files
+}
+
+/// The index at `path`, empty if it's missing or unreadable
+fn load_index(path: &Path) -> ArchiveIndex {
+    ArchiveIndex::load_from(path).unwrap_or_else(|e| {
+        warn!(path = %path.display(), error = %e, "Failed to read archive index");
+        ArchiveIndex::default()
+    })
+}
+
+/// Vaults an index has entries or a stamp for
+fn known_vaults(index: &ArchiveIndex) -> HashSet<String> {
+    index
+        .vaults
+        .keys()
+        .chain(index.generations.keys())
+        .cloned()
+        .collect()
+}
+
+/// Drop a vault from an index, returning whether it had it
+fn forget_vault(index: &mut ArchiveIndex, vault_id: &str) -> bool {
+    let had_entries = index.vaults.remove(vault_id).is_some();
+    let had_stamp = index.generations.remove(vault_id).is_some();
+    had_entries || had_stamp
+}
+
+/// The newest of `items` by generation, then write time
+fn newest<'a, T>(
+    items: impl Iterator<Item = &'a T>,
+    stamp: impl Fn(&T) -> &GenerationStamp,
+) -> Option<&'a T>
+where
+    T: 'a,
+{
+    items.max_by_key(|item| {
+        let stamp = stamp(*item);
+        (
+            stamp.generation,
+            stamp.last_writer.as_ref().map(|writer| writer.timestamp),
+        )
+    })
+}
+
+/// The stamp with the highest generation (default if there are none)
+fn highest<'a>(stamps: impl Iterator<Item = &'a GenerationStamp>) -> GenerationStamp {
+    stamps
+        .max_by_key(|stamp| stamp.generation)
+        .cloned()
+        .unwrap_or_default()
+}
+
+/// Union of every copy's entries, newest copy first for entries that differ
+///
+/// An entry every copy has is kept as is. Any other is kept only if its
+/// archive is in `vaults_dir` and, when a hash was recorded, still matches
+/// it; the names of the rest are returned.
+fn merge_entries(
+    indexes: &[IndexFile],
+    vaults_dir: &Path,
+) -> (Vec<ArchiveIndexEntry>, Vec<String>) {
+    let mut newest_first: Vec<&IndexFile> = indexes.iter().collect();
+    newest_first.sort_by_key(|i| {
+        std::cmp::Reverse((
+            i.stamp.generation,
+            i.stamp.last_writer.as_ref().map(|writer| writer.timestamp),
+        ))
+    });
+
+    let mut seen = HashSet::new();
+    let mut merged = Vec::new();
+    let mut dropped = Vec::new();
+    for entry in newest_first.iter().flat_map(|i| &i.entries) {
+        if !seen.insert(entry.archive_id.clone()) {
+            continue;
+        }
+        let common = indexes.iter().all(|i| i.entries.contains(entry));
+        if common || archive_checks_out(vaults_dir, entry) {
+            merged.push(entry.clone());
+        } else {
+            dropped.push(entry.archive_name.clone());
+        }
+    }
+
+    merged.sort_by_key(|entry| entry.created_at);
+    dropped.sort();
+    dropped.dedup();
+    (merged, dropped)
+}
+
+/// Whether an entry's archive file exists and matches its recorded hash
+fn archive_checks_out(vaults_dir: &Path, entry: &ArchiveIndexEntry) -> bool {
+    let path = vaults_dir.join(&entry.archive_name);
+    path.is_file()
+        && entry.archive_sha256.as_ref().is_none_or(|expected| {
+            calculate_file_hash_with(&path, HashAlgorithm::Sha256)
+                .is_ok_and(|actual| actual.eq_ignore_ascii_case(expected))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::shared::infrastructure::DeviceInfo;
+    use crate::services::vault::infrastructure::persistence::stamp_manifest;
+    use chrono::{DateTime, Utc};
+    use tempfile::TempDir;
+
+    const VAULT_ID: &str = "vault-001";
+
+    struct Devices {
+        _temp: TempDir,
+        desktop: ConflictLocations,
+        laptop: ConflictLocations,
+    }
+
+    impl Devices {
+        fn new() -> Self {
+            let temp = TempDir::new().unwrap();
+            let store = temp.path().join("store");
+            let vaults_dir = temp.path().join("vaults");
+            fs::create_dir_all(store.join(STORE_MANIFESTS_DIR)).unwrap();
+            fs::create_dir_all(&vaults_dir).unwrap();
+            let file_sets = vec![VaultFileSet {
+                manifest_dir: store.join(STORE_MANIFESTS_DIR),
+                index_path: ArchiveIndex::store_index_path(&store),
+            }];
+            let device = |name: &str| ConflictLocations {
+                ledger: GenerationLedger::at(
+                    name,
+                    format!("{name}-host"),
+                    temp.path().join(format!("{name}.json")),
+                ),
+                file_sets: file_sets.clone(),
+                vaults_dir: vaults_dir.clone(),
+            };
+
+            Self {
+                desktop: device("desktop"),
+                laptop: device("laptop"),
+                _temp: temp,
+            }
+        }
+
+        fn manifest_path(&self) -> PathBuf {
+            self.desktop.file_sets[0]
+                .manifest_dir
+                .join("Family.manifest")
+        }
+
+        fn index_path(&self) -> PathBuf {
+            self.desktop.file_sets[0].index_path.clone()
+        }
+
+        /// Both devices edit the vault in turn, then each changes it while the
+        /// other's change hasn't synced: the sync service keeps the laptop's
+        /// files and sets the desktop's aside as conflicted copies
+        fn fork(&self) {
+            save_manifest(&self.desktop, "Family");
+            save_index(&self.desktop, &["a"]);
+            save_manifest(&self.laptop, "Family");
+            save_index(&self.laptop, &["a", "b"]);
+
+            let manifest_copy = self
+                .manifest_path()
+                .with_file_name("Family (desktop-host's conflicted copy).manifest");
+            let index_copy = self
+                .index_path()
+                .with_file_name("archive_index (desktop-host's conflicted copy).json");
+            diverge(
+                &self.manifest_path(),
+                &manifest_copy,
+                || save_manifest(&self.desktop, "Family (desktop)"),
+                || save_manifest(&self.laptop, "Family (laptop)"),
+            );
+            diverge(
+                &self.index_path(),
+                &index_copy,
+                || save_index(&self.desktop, &["a", "b", "c"]),
+                || save_index(&self.laptop, &["a", "b", "d"]),
+            );
+        }
+
+        fn archive(&self, name: &str, content: &[u8]) -> PathBuf {
+            let path = self.desktop.vaults_dir.join(name);
+            fs::write(&path, content).unwrap();
+            path
+        }
+    }
+
+    /// Run `first` and `second` on the same starting file, leaving the
+    /// second's write in place and the first's at `copy`
+    fn diverge(path: &Path, copy: &Path, first: impl FnOnce(), second: impl FnOnce()) {
+        let start = fs::read(path).unwrap();
+        first();
+        let written_first = fs::read(path).unwrap();
+        fs::write(path, start).unwrap();
+        second();
+        fs::write(copy, written_first).unwrap();
+    }
+
+    fn metadata(label: &str) -> VaultMetadata {
+        let device_info = DeviceInfo {
+            machine_id: "test".to_string(),
+            machine_label: "test".to_string(),
+            created_at: Utc::now(),
+            app_version: "2.0.0".to_string(),
+        };
+        VaultMetadata::new(
+            VAULT_ID.to_string(),
+            label.to_string(),
+            None,
+            "Family".to_string(),
+            &device_info,
+            None,
+            vec![],
+            vec![],
+            0,
+            0,
+        )
+    }
+
+    fn save_manifest(device: &ConflictLocations, label: &str) {
+        let path = device.file_sets[0].manifest_dir.join("Family.manifest");
+        let stamped = stamp_manifest(&device.ledger, &metadata(label), &path).unwrap();
+        fs::write(&path, serde_json::to_string_pretty(&stamped).unwrap()).unwrap();
+        device
+            .ledger
+            .record(
+                VAULT_ID,
+                GenerationFile::Manifest,
+                stamped.generation_stamp.generation,
+            )
+            .unwrap();
+    }
+
+    fn save_index(device: &ConflictLocations, archive_ids: &[&str]) {
+        let mut index = ArchiveIndex::default();
+        index.vaults.insert(
+            VAULT_ID.to_string(),
+            archive_ids.iter().map(|id| entry(id)).collect(),
+        );
+        index
+            .save_stamped(&device.file_sets[0].index_path, &device.ledger)
+            .unwrap();
+    }
+
+    fn entry(archive_id: &str) -> ArchiveIndexEntry {
+        let minute = i64::from(archive_id.as_bytes()[0]);
+        ArchiveIndexEntry {
+            archive_id: archive_id.to_string(),
+            vault_id: VAULT_ID.to_string(),
+            archive_name: format!("Family-{archive_id}.age"),
+            encryption_revision: 1,
+            created_at: DateTime::from_timestamp(1_700_000_000 + minute * 60, 0).unwrap(),
+            file_count: 1,
+            comment: None,
+            comment_updated_at: None,
+            immutable: false,
+            parity: None,
+            migrated_from: None,
+            prune_marked: false,
+            remapped_from: None,
+            archive_sha256: None,
+            recipients: Vec::new(),
+        }
+    }
+
+    fn archive_ids(device: &ConflictLocations) -> Vec<String> {
+        load_index(&device.file_sets[0].index_path)
+            .entries(VAULT_ID)
+            .iter()
+            .map(|entry| entry.archive_id.clone())
+            .collect()
+    }
+
+    fn label(devices: &Devices) -> String {
+        let content = fs::read_to_string(devices.manifest_path()).unwrap();
+        serde_json::from_str::<VaultMetadata>(&content)
+            .unwrap()
+            .vault
+            .label
+    }
+
+    #[test]
+    fn test_interleaved_edits_are_not_a_conflict() {
+        let devices = Devices::new();
+        save_manifest(&devices.desktop, "Family");
+        save_manifest(&devices.laptop, "Family");
+        save_manifest(&devices.desktop, "Family");
+        save_index(&devices.desktop, &["a"]);
+        save_index(&devices.laptop, &["a", "b"]);
+
+        let service = VaultConflictService::new();
+        assert!(service.conflicts_in(&devices.desktop).is_empty());
+        assert!(service.conflicts_in(&devices.laptop).is_empty());
+        assert!(matches!(
+            service.resolve_in(&devices.desktop, VAULT_ID, ConflictStrategy::PreferLocal),
+            Err(VaultError::InvalidOperation(_))
+        ));
+    }
+
+    #[test]
+    fn test_fork_is_detected_and_blocks_writes() {
+        let devices = Devices::new();
+        devices.fork();
+        let service = VaultConflictService::new();
+
+        let conflicts = service.conflicts_in(&devices.desktop);
+        assert_eq!(conflicts.len(), 1);
+        let evidence = &conflicts[0].evidence;
+        assert!(evidence.iter().any(|e| matches!(
+            e,
+            ConflictEvidence::HiddenWrite {
+                file: GenerationFile::Manifest,
+                on_disk: 3,
+                last_written: 3,
+                ..
+            }
+        )));
+        assert!(evidence.iter().any(|e| matches!(
+            e,
+            ConflictEvidence::HiddenWrite {
+                file: GenerationFile::ArchiveIndex,
+                ..
+            }
+        )));
+        // The laptop's files won, but it still sees the desktop's copies
+        assert_eq!(
+            service.conflicts_in(&devices.laptop)[0].evidence.len(),
+            2,
+            "both conflicted copies"
+        );
+
+        assert!(
+            stamp_manifest(
+                &devices.desktop.ledger,
+                &metadata("Family"),
+                &devices.manifest_path()
+            )
+            .is_err()
+        );
+        let mut index = load_index(&devices.index_path());
+        index.record(entry("e"));
+        assert!(
+            index
+                .save_stamped(&devices.index_path(), &devices.laptop.ledger)
+                .is_err()
+        );
+        assert!(matches!(
+            service.ensure_resolved_in(&devices.laptop, VAULT_ID),
+            Err(VaultError::ConflictSuspected { .. })
+        ));
+    }
+
+    #[test]
+    fn test_prefer_local_keeps_this_devices_copies() {
+        let devices = Devices::new();
+        devices.fork();
+        let service = VaultConflictService::new();
+
+        let resolution = service
+            .resolve_in(&devices.desktop, VAULT_ID, ConflictStrategy::PreferLocal)
+            .unwrap();
+
+        assert_eq!(label(&devices), "Family (desktop)");
+        assert_eq!(archive_ids(&devices.desktop), ["a", "b", "c"]);
+        assert_eq!(resolution.manifest_generation, 4);
+        assert_eq!(resolution.archive_count, 3);
+        assert_eq!(resolution.removed_copies.len(), 2);
+        assert!(service.conflicts_in(&devices.desktop).is_empty());
+        assert!(service.conflicts_in(&devices.laptop).is_empty());
+
+        // Both devices write normally again
+        save_manifest(&devices.laptop, "Family");
+        save_index(&devices.laptop, &["a", "b", "c", "e"]);
+        save_manifest(&devices.desktop, "Family");
+    }
+
+    #[test]
+    fn test_prefer_remote_keeps_the_other_devices_copies() {
+        let devices = Devices::new();
+        devices.fork();
+        let service = VaultConflictService::new();
+
+        service
+            .resolve_in(&devices.desktop, VAULT_ID, ConflictStrategy::PreferRemote)
+            .unwrap();
+
+        assert_eq!(label(&devices), "Family (laptop)");
+        assert_eq!(archive_ids(&devices.desktop), ["a", "b", "d"]);
+        assert!(service.conflicts_in(&devices.desktop).is_empty());
+        assert!(service.conflicts_in(&devices.laptop).is_empty());
+    }
+
+    #[test]
+    fn test_merge_keeps_entries_whose_archives_check_out() {
+        let devices = Devices::new();
+        devices.fork();
+        let c = devices.archive("Family-c.age", b"archive c");
+        devices.archive("Family-d.age", b"changed since");
+        // Pin the expected hashes in the copies' entries
+        let sha256 = calculate_file_hash_with(&c, HashAlgorithm::Sha256).unwrap();
+        for path in conflicted_copies(&devices.index_path())
+            .into_iter()
+            .chain([devices.index_path()])
+        {
+            let mut index = load_index(&path);
+            for entry in index.vaults.values_mut().flatten() {
+                entry.archive_sha256 = match entry.archive_id.as_str() {
+                    "c" => Some(sha256.to_uppercase()),
+                    "d" => Some("00".repeat(32)),
+                    _ => None,
+                };
+            }
+            index.save_to(&path).unwrap();
+        }
+        let service = VaultConflictService::new();
+
+        let resolution = service
+            .resolve_in(&devices.laptop, VAULT_ID, ConflictStrategy::MergeIndexes)
+            .unwrap();
+
+        // "a" and "b" are in both copies; "d" no longer matches its archive
+        assert_eq!(archive_ids(&devices.laptop), ["a", "b", "c"]);
+        assert_eq!(resolution.dropped_archives, ["Family-d.age"]);
+        assert!(service.conflicts_in(&devices.desktop).is_empty());
+        assert!(service.conflicts_in(&devices.laptop).is_empty());
+    }
+}
@@ -0,0 +1,384 @@
+//! Vault Item Service
+//!
+//! CRUD for the structured items stored in a vault manifest. Links are
+//! checked against the latest archive's manifest and the archive index when
+//! they are added; links that stop resolving after a later backup are kept
+//! and reported as missing rather than silently dropped.
+//!
+//! Titles and notes are user content: log counts and IDs, never their text.
+
+use crate::prelude::*;
+use crate::services::vault::application::services::ArchiveService;
+use crate::services::vault::domain::models::{
+    ArchiveIndexEntry, ItemLinkTargets, VaultItem, VaultItemInput, VaultItemStatistics,
+    VaultItemView, item_statistics,
+};
+use crate::services::vault::domain::{VaultError, VaultResult};
+use crate::services::vault::infrastructure::VaultRepository;
+use crate::services::vault::infrastructure::persistence::metadata::VaultMetadata;
+use chrono::{DateTime, Utc};
+
+/// Service for vault items
+#[derive(Debug)]
+pub struct VaultItemService {
+    repository: VaultRepository,
+    archive_service: ArchiveService,
+}
+
+impl VaultItemService {
+    pub fn new() -> Self {
+        Self {
+            repository: VaultRepository::new(),
+            archive_service: ArchiveService::new(),
+        }
+    }
+
+    /// Add an item to a vault
+    pub async fn add_item(&self, vault_id: &str, input: VaultItemInput) -> VaultResult<VaultItem> {
+        let mut metadata = self.repository.get_vault(vault_id).await?;
+        let archives = self.archive_service.list_archives(vault_id)?;
+
+        let current = metadata.clone();
+
+        let item = Self::add_to(
+            &mut metadata,
+            &link_targets(&current, &archives),
+            input,
+            Utc::now(),
+        )?;
+        self.repository.save_vault(&metadata).await?;
+        Ok(item)
+    }
+
+    /// Replace an item's editable fields
+    pub async fn update_item(
+        &self,
+        vault_id: &str,
+        item_id: &str,
+        input: VaultItemInput,
+    ) -> VaultResult<VaultItem> {
+        let mut metadata = self.repository.get_vault(vault_id).await?;
+        let archives = self.archive_service.list_archives(vault_id)?;
+
+        let current = metadata.clone();
+
+        let item = Self::update_in(
+            &mut metadata,
+            &link_targets(&current, &archives),
+            item_id,
+            input,
+            Utc::now(),
+        )?;
+        self.repository.save_vault(&metadata).await?;
+        Ok(item)
+    }
+
+    /// Remove an item, returning it
+    pub async fn remove_item(&self, vault_id: &str, item_id: &str) -> VaultResult<VaultItem> {
+        let mut metadata = self.repository.get_vault(vault_id).await?;
+        let item = Self::remove_from(&mut metadata, item_id)?;
+        self.repository.save_vault(&metadata).await?;
+        Ok(item)
+    }
+
+    /// A vault's items with their unresolved links
+    pub async fn list_items(&self, vault_id: &str) -> VaultResult<Vec<VaultItemView>> {
+        let metadata = self.repository.get_vault(vault_id).await?;
+        let archives = self.archive_service.list_archives(vault_id)?;
+        Ok(Self::views(&metadata, &link_targets(&metadata, &archives)))
+    }
+
+    /// Item completion stats for a manifest
+    ///
+    /// Archive links are only checked when the index can be read, so an
+    /// unreadable index never reports every archive link as missing.
+    pub fn statistics(&self, metadata: &VaultMetadata) -> VaultItemStatistics {
+        match self.archive_service.list_archives(metadata.vault_id()) {
+            Ok(archives) => item_statistics(&metadata.items, &link_targets(metadata, &archives)),
+            Err(e) => {
+                warn!(error = %e, "Archive index unavailable for item statistics");
+                let targets = ItemLinkTargets::paths_only(
+                    metadata.content.files.iter().map(|f| f.path.as_str()),
+                );
+                item_statistics(&metadata.items, &targets)
+            }
+        }
+    }
+
+    /// Validate and append an item
+    pub fn add_to(
+        metadata: &mut VaultMetadata,
+        targets: &ItemLinkTargets,
+        input: VaultItemInput,
+        now: DateTime<Utc>,
+    ) -> VaultResult<VaultItem> {
+        let input = input.normalize()?;
+        check_new_links(targets, &input, None)?;
+
+        let item = VaultItem::new(input, now);
+        debug!(
+            vault_id = %metadata.vault_id(),
+            item_id = %item.id,
+            linked_paths = item.linked_paths.len(),
+            "Added vault item"
+        );
+
+        metadata.items.push(item.clone());
+        Ok(item)
+    }
+
+    /// Validate and apply an edit
+    ///
+    /// Links the item already had may stay even if they no longer resolve,
+    /// so an item can still be edited after its files left the archive.
+    pub fn update_in(
+        metadata: &mut VaultMetadata,
+        targets: &ItemLinkTargets,
+        item_id: &str,
+        input: VaultItemInput,
+        now: DateTime<Utc>,
+    ) -> VaultResult<VaultItem> {
+        let input = input.normalize()?;
+        let item = metadata
+            .items
+            .iter_mut()
+            .find(|item| item.id == item_id)
+            .ok_or_else(|| item_not_found(item_id))?;
+
+        check_new_links(targets, &input, Some(&*item))?;
+        item.apply(input, now);
+
+        debug!(item_id, completed = item.completed, "Updated vault item");
+        Ok(item.clone())
+    }
+
+    /// Remove an item by ID
+    pub fn remove_from(metadata: &mut VaultMetadata, item_id: &str) -> VaultResult<VaultItem> {
+        let position = metadata
+            .items
+            .iter()
+            .position(|item| item.id == item_id)
+            .ok_or_else(|| item_not_found(item_id))?;
+
+        debug!(item_id, "Removed vault item");
+        Ok(metadata.items.remove(position))
+    }
+
+    /// Items paired with their unresolved links
+    pub fn views(metadata: &VaultMetadata, targets: &ItemLinkTargets) -> Vec<VaultItemView> {
+        metadata
+            .items
+            .iter()
+            .map(|item| targets.view(item))
+            .collect()
+    }
+}
+
+impl Default for VaultItemService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Link targets for a manifest: its files plus the vault's indexed archives
+pub fn link_targets<'a>(
+    metadata: &'a VaultMetadata,
+    archives: &'a [ArchiveIndexEntry],
+) -> ItemLinkTargets<'a> {
+    ItemLinkTargets::new(
+        metadata.content.files.iter().map(|f| f.path.as_str()),
+        archives.iter().map(|a| a.archive_id.as_str()),
+    )
+}
+
+/// Reject links that don't resolve, unless `existing` already had them
+fn check_new_links(
+    targets: &ItemLinkTargets,
+    input: &VaultItemInput,
+    existing: Option<&VaultItem>,
+) -> VaultResult<()> {
+    let is_new_path = |path: &String| existing.is_none_or(|item| !item.linked_paths.contains(path));
+    let is_new_archive =
+        |id: &String| existing.is_none_or(|item| !item.linked_archive_ids.contains(id));
+
+    let missing_paths: Vec<String> = targets
+        .missing_paths(&input.linked_paths)
+        .into_iter()
+        .filter(is_new_path)
+        .collect();
+    if !missing_paths.is_empty() {
+        return Err(VaultError::InvalidOperation(format!(
+            "Not in the latest archive: {}",
+            missing_paths.join(", ")
+        )));
+    }
+
+    let missing_archives: Vec<String> = targets
+        .missing_archive_ids(&input.linked_archive_ids)
+        .into_iter()
+        .filter(is_new_archive)
+        .collect();
+    if !missing_archives.is_empty() {
+        return Err(VaultError::InvalidOperation(format!(
+            "Archive not found: {}",
+            missing_archives.join(", ")
+        )));
+    }
+
+    Ok(())
+}
+
+fn item_not_found(item_id: &str) -> VaultError {
+    VaultError::InvalidOperation(format!("Item '{}' not found", item_id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::file::infrastructure::file_operations::{HashAlgorithm, RawPath};
+    use crate::services::shared::infrastructure::DeviceInfo;
+    use crate::services::vault::domain::models::VaultItemCategory;
+    use crate::services::vault::infrastructure::persistence::metadata::VaultFileEntry;
+
+    fn file(path: &str) -> VaultFileEntry {
+        VaultFileEntry {
+            path: path.to_string(),
+            raw_path: RawPath::from_display(path),
+            lossy_name: false,
+            size: 1,
+            sha256: "abc".to_string(),
+            hash_algorithm: HashAlgorithm::Sha256,
+            digest: None,
+            ownership: None,
+            content_type: None,
+            content_type_mismatch: false,
+            transform: None,
+        }
+    }
+
+    fn manifest(paths: &[&str]) -> VaultMetadata {
+        let device_info = DeviceInfo {
+            machine_id: "machine-1".to_string(),
+            machine_label: "laptop".to_string(),
+            created_at: Utc::now(),
+            app_version: "2.0.0".to_string(),
+        };
+        VaultMetadata::new(
+            "vault-001".to_string(),
+            "Estate".to_string(),
+            None,
+            "Estate".to_string(),
+            &device_info,
+            None,
+            vec![],
+            paths.iter().map(|p| file(p)).collect(),
+            paths.len(),
+            paths.len() as u64,
+        )
+    }
+
+    fn input(title: &str, linked_paths: &[&str]) -> VaultItemInput {
+        VaultItemInput {
+            category: VaultItemCategory::WalletDescriptor,
+            title: title.to_string(),
+            linked_paths: linked_paths.iter().map(|p| p.to_string()).collect(),
+            linked_archive_ids: vec![],
+            note: None,
+            completed: false,
+        }
+    }
+
+    #[test]
+    fn test_item_crud() {
+        let mut metadata = manifest(&["wallet/descriptor.txt"]);
+        let snapshot = metadata.clone();
+        let targets = link_targets(&snapshot, &[]);
+
+        let added = VaultItemService::add_to(
+            &mut metadata,
+            &targets,
+            input("Descriptor", &["wallet/descriptor.txt"]),
+            Utc::now(),
+        )
+        .unwrap();
+        assert_eq!(metadata.items.len(), 1);
+
+        let mut edit = input("Main wallet descriptor", &["wallet/descriptor.txt"]);
+        edit.completed = true;
+        let updated =
+            VaultItemService::update_in(&mut metadata, &targets, &added.id, edit, Utc::now())
+                .unwrap();
+        assert_eq!(updated.id, added.id);
+        assert_eq!(updated.title, "Main wallet descriptor");
+        assert!(updated.completed);
+        assert_eq!(updated.created_at, added.created_at);
+
+        let views = VaultItemService::views(&metadata, &targets);
+        assert_eq!(views.len(), 1);
+        assert!(views[0].missing_paths.is_empty());
+
+        let removed = VaultItemService::remove_from(&mut metadata, &added.id).unwrap();
+        assert_eq!(removed.id, added.id);
+        assert!(metadata.items.is_empty());
+        assert!(VaultItemService::remove_from(&mut metadata, &added.id).is_err());
+    }
+
+    #[test]
+    fn test_links_must_resolve_when_added() {
+        let mut metadata = manifest(&["a.txt"]);
+        let snapshot = metadata.clone();
+        let targets = link_targets(&snapshot, &[]);
+
+        let result = VaultItemService::add_to(
+            &mut metadata,
+            &targets,
+            input("Lawyer", &["b.txt"]),
+            Utc::now(),
+        );
+        assert!(matches!(result, Err(VaultError::InvalidOperation(_))));
+
+        let mut archived = input("Old backup", &[]);
+        archived.linked_archive_ids = vec!["archive-1".to_string()];
+        let result = VaultItemService::add_to(&mut metadata, &targets, archived, Utc::now());
+        assert!(matches!(result, Err(VaultError::InvalidOperation(_))));
+        assert!(metadata.items.is_empty());
+    }
+
+    #[test]
+    fn test_dangling_links_after_manifest_change() {
+        let mut metadata = manifest(&["wallet/descriptor.txt", "pin-location.txt"]);
+        let snapshot = metadata.clone();
+        let targets = link_targets(&snapshot, &[]);
+        let item = VaultItemService::add_to(
+            &mut metadata,
+            &targets,
+            input("PIN location", &["pin-location.txt"]),
+            Utc::now(),
+        )
+        .unwrap();
+
+        // A new backup no longer contains the linked file
+        let mut next = manifest(&["wallet/descriptor.txt"]);
+        next.inherit_vault_settings(&metadata);
+        let snapshot = next.clone();
+        let targets = link_targets(&snapshot, &[]);
+
+        let views = VaultItemService::views(&next, &targets);
+        assert_eq!(views[0].missing_paths, vec!["pin-location.txt"]);
+
+        // The item stays editable with its dangling link, but a new one is rejected
+        let mut edit = input("PIN location", &["pin-location.txt"]);
+        edit.completed = true;
+        VaultItemService::update_in(&mut next, &targets, &item.id, edit, Utc::now()).unwrap();
+
+        let edit = input("PIN location", &["pin-location.txt", "gone.txt"]);
+        assert!(
+            VaultItemService::update_in(&mut next, &targets, &item.id, edit, Utc::now()).is_err()
+        );
+
+        let stats = item_statistics(&next.items, &targets);
+        assert_eq!(stats.total_items, 1);
+        assert_eq!(stats.completed_items, 1);
+        assert_eq!(stats.items_with_missing_links, 1);
+    }
+}
@@ -13,6 +13,10 @@
 use crate::services::vault::infrastructure::persistence::metadata::{
     RecipientInfo, RecipientType, VaultFileEntry, VaultMetadata,
 };
+use crate::services::vault::infrastructure::persistence::vault_persistence::record_manifest_write;
+use crate::services::vault::infrastructure::persistence::{
+    GenerationLedger, signed_manifest_json, stamp_manifest,
+};
 use std::path::Path;
 
 /// Service for managing vault manifests (R2)
@@ -50,6 +54,15 @@ pub fn load_or_create(
         }
     }
 
+    /// Load the saved manifest from non-sync storage, if there is one
+    pub fn load_saved(&self, vault_name: &str) -> Result<Option<VaultMetadata>, StorageError> {
+        let manifest_path = get_vault_manifest_path(vault_name)?;
+        if !manifest_path.exists() {
+            return Ok(None);
+        }
+        self.load_manifest(&manifest_path).map(Some)
+    }
+
     /// Load manifest from file
     fn load_manifest(&self, path: &Path) -> Result<VaultMetadata, StorageError> {
         let content = std::fs::read_to_string(path).map_err(|e| StorageError::FileReadFailed {
@@ -123,8 +136,8 @@ pub fn build_from_vault_and_registry(
         let mut recipients = Vec::new();
         for key_id in vault_keys {
             if let Ok(registry_entry) = self.key_registry.get_key(key_id) {
-                let recipient = Self::registry_entry_to_recipient(key_id, &registry_entry);
-                recipients.push(recipient);
+                // Reserved YubiKey slots join once their hardware is registered
+                recipients.extend(Self::registry_entry_to_recipient(key_id, &registry_entry));
             } else {
                 warn!(key_id, "Key not found in registry, skipping");
             }
@@ -147,9 +160,9 @@ pub fn build_from_vault_and_registry(
         ))
     }
 
-    /// Convert KeyEntry to RecipientInfo
-    fn registry_entry_to_recipient(key_id: &str, entry: &KeyEntry) -> RecipientInfo {
-        match entry {
+    /// Convert KeyEntry to RecipientInfo (`None` for a reserved YubiKey slot)
+    fn registry_entry_to_recipient(key_id: &str, entry: &KeyEntry) -> Option<RecipientInfo> {
+        let recipient = match entry {
             KeyEntry::Passphrase {
                 label,
                 public_key,
@@ -204,7 +217,9 @@ fn registry_entry_to_recipient(key_id: &str, entry: &KeyEntry) -> RecipientInfo
                 label: label.clone(),
                 created_at: *created_at,
             },
-        }
+            KeyEntry::YubikeyPending { .. } => return None,
+        };
+        Some(recipient)
     }
 
     /// Increment manifest version and save
@@ -218,21 +233,24 @@ pub fn increment_version_and_save(
     }
 
     /// Save manifest to non-sync storage (atomic write)
+    ///
+    /// Refused while the vault is suspected to be in conflict with another
+    /// device's copy.
     pub fn save_manifest(&self, manifest: &VaultMetadata) -> Result<(), StorageError> {
         let manifest_path = get_vault_manifest_path(&manifest.vault.sanitized_name)?;
-
-        let json = serde_json::to_string_pretty(manifest).map_err(|e| {
-            StorageError::SerializationFailed {
-                message: format!("Failed to serialize manifest: {}", e),
-            }
-        })?;
-
-        atomic_write_sync(&manifest_path, json.as_bytes()).map_err(|e| {
-            StorageError::FileWriteFailed {
+        let write_failed =
+            |e: Box<dyn std::error::Error + Send + Sync>| StorageError::FileWriteFailed {
                 path: manifest_path.clone(),
                 source: std::io::Error::other(e),
-            }
-        })?;
+            };
+
+        let ledger = GenerationLedger::load().map_err(write_failed)?;
+        let manifest = stamp_manifest(&ledger, manifest, &manifest_path)
+            .map_err(|e| write_failed(e.into()))?;
+        let json = signed_manifest_json(&manifest)?;
+
+        atomic_write_sync(&manifest_path, json.as_bytes()).map_err(write_failed)?;
+        record_manifest_write(&ledger, &manifest).map_err(write_failed)?;
 
         debug!(
             vault = %manifest.label(),
@@ -273,9 +291,12 @@ fn test_registry_entry_to_recipient_passphrase() {
             vault_associations: vec![],
             deactivated_at: None,
             previous_lifecycle_status: None,
+            key_location: None,
+            passphrase_policy: 0,
         };
 
-        let recipient = VaultMetadataService::registry_entry_to_recipient("test-key-id", &entry);
+        let recipient =
+            VaultMetadataService::registry_entry_to_recipient("test-key-id", &entry).unwrap();
 
         assert_eq!(recipient.label, "test-key");
         assert_eq!(recipient.public_key, "age1test123");
@@ -304,9 +325,11 @@ fn test_registry_entry_to_recipient_yubikey() {
             vault_associations: vec![],
             deactivated_at: None,
             previous_lifecycle_status: None,
+            last_reader: None,
         };
 
-        let recipient = VaultMetadataService::registry_entry_to_recipient("test-key-id", &entry);
+        let recipient =
+            VaultMetadataService::registry_entry_to_recipient("test-key-id", &entry).unwrap();
 
         assert_eq!(recipient.label, "YubiKey-12345");
         match recipient.recipient_type {
@@ -0,0 +1,228 @@
+//! Vault Order Service
+//!
+//! Applies the user's vault order to the vault list: favorites first, then
+//! the position set by `reorder`, then name. The stored order is reconciled
+//! with the vaults that exist whenever it is used, so deleted vaults drop out
+//! and new ones join at the end without any bookkeeping at create or delete.
+
+use crate::commands::listing::compare_text;
+use crate::prelude::*;
+use crate::services::vault::domain::models::VaultSummary;
+use crate::services::vault::domain::{VaultError, VaultResult};
+use crate::services::vault::infrastructure::persistence::VaultOrder;
+use std::cmp::Ordering;
+use std::path::{Path, PathBuf};
+
+/// Service for the user's vault order
+#[derive(Debug, Default)]
+pub struct VaultOrderService {
+    /// Order file location; the config directory's file when `None`
+    order_path: Option<PathBuf>,
+}
+
+impl VaultOrderService {
+    pub fn new() -> Self {
+        Self { order_path: None }
+    }
+
+    /// Service over the order file at `order_path`
+    pub fn at(order_path: PathBuf) -> Self {
+        Self {
+            order_path: Some(order_path),
+        }
+    }
+
+    /// Fill in each vault's favorite flag and position and sort the list
+    ///
+    /// An unreadable order file leaves the list in name order rather than
+    /// failing it.
+    pub fn apply(&self, vaults: &mut [VaultSummary]) -> VaultResult<()> {
+        let path = self.order_path()?;
+        let order = match VaultOrder::load_from(&path) {
+            Ok(mut order) => {
+                if order.reconcile(&creation_order(vaults))
+                    && let Err(e) = order.save_to(&path)
+                {
+                    warn!(error = %e, "Failed to save reconciled vault order");
+                }
+                order
+            }
+            Err(e) => {
+                warn!(error = %e, "Failed to read vault order, listing by name");
+                VaultOrder::default()
+            }
+        };
+
+        for vault in vaults.iter_mut() {
+            let entry = order.entry(&vault.id);
+            vault.favorite = entry.is_some_and(|entry| entry.favorite);
+            vault.sort_index = entry.map_or(u32::MAX, |entry| entry.sort_index);
+        }
+        vaults.sort_by(|a, b| compare_user_order(a, b).then_with(|| a.id.cmp(&b.id)));
+        Ok(())
+    }
+
+    /// Store a new order for `vaults`, which `ordered_ids` must list exactly
+    pub fn reorder(&self, vaults: &[VaultSummary], ordered_ids: &[String]) -> VaultResult<()> {
+        let path = self.order_path()?;
+        let mut order = load(&path)?;
+        order.reconcile(&creation_order(vaults));
+        order.reorder(ordered_ids).map_err(|mismatch| {
+            VaultError::InvalidOperation(format!(
+                "The order must list every vault once ({mismatch})"
+            ))
+        })?;
+        save(&order, &path)?;
+        info!(vault_count = ordered_ids.len(), "Reordered vaults");
+        Ok(())
+    }
+
+    /// Mark or unmark one of `vaults` as a favorite
+    pub fn set_favorite(
+        &self,
+        vaults: &[VaultSummary],
+        vault_id: &str,
+        favorite: bool,
+    ) -> VaultResult<()> {
+        let path = self.order_path()?;
+        let mut order = load(&path)?;
+        order.reconcile(&creation_order(vaults));
+        if !order.set_favorite(vault_id, favorite) {
+            return Err(VaultError::NotFound(vault_id.to_string()));
+        }
+        save(&order, &path)
+    }
+
+    fn order_path(&self) -> VaultResult<PathBuf> {
+        match &self.order_path {
+            Some(path) => Ok(path.clone()),
+            None => VaultOrder::get_order_path().map_err(|e| {
+                VaultError::StorageError(format!("Failed to locate vault order: {}", e))
+            }),
+        }
+    }
+}
+
+/// Favorites first, then position, then name
+pub fn compare_user_order(a: &VaultSummary, b: &VaultSummary) -> Ordering {
+    b.favorite
+        .cmp(&a.favorite)
+        .then(a.sort_index.cmp(&b.sort_index))
+        .then_with(|| compare_text(&a.name, &b.name))
+}
+
+/// Vault ids oldest first, the order new vaults are appended in
+fn creation_order(vaults: &[VaultSummary]) -> Vec<&str> {
+    let mut vaults: Vec<&VaultSummary> = vaults.iter().collect();
+    vaults.sort_by(|a, b| {
+        a.created_at
+            .cmp(&b.created_at)
+            .then_with(|| a.id.cmp(&b.id))
+    });
+    vaults.into_iter().map(|vault| vault.id.as_str()).collect()
+}
+
+fn load(path: &Path) -> VaultResult<VaultOrder> {
+    VaultOrder::load_from(path)
+        .map_err(|e| VaultError::StorageError(format!("Failed to read vault order: {}", e)))
+}
+
+fn save(order: &VaultOrder, path: &Path) -> VaultResult<()> {
+    order
+        .save_to(path)
+        .map_err(|e| VaultError::StorageError(format!("Failed to save vault order: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, Utc};
+    use tempfile::TempDir;
+
+    fn vaults(names: &[&str]) -> Vec<VaultSummary> {
+        let start = Utc::now();
+        names
+            .iter()
+            .enumerate()
+            .map(|(i, name)| VaultSummary {
+                id: format!("vault-{name}"),
+                name: name.to_string(),
+                description: None,
+                created_at: start + Duration::seconds(i as i64),
+                key_count: 0,
+                favorite: false,
+                sort_index: 0,
+                sync_status: Default::default(),
+            })
+            .collect()
+    }
+
+    fn names(vaults: &[VaultSummary]) -> Vec<&str> {
+        vaults.iter().map(|vault| vault.name.as_str()).collect()
+    }
+
+    #[test]
+    fn test_favorites_first_then_position_then_name() {
+        let temp = TempDir::new().unwrap();
+        let service = VaultOrderService::at(temp.path().join("vault_order.json"));
+        let all = vaults(&["Taxes", "Family", "Business", "Crypto"]);
+
+        // Unordered vaults keep creation order
+        let mut listed = all.clone();
+        service.apply(&mut listed).unwrap();
+        assert_eq!(
+            names(&listed),
+            vec!["Taxes", "Family", "Business", "Crypto"]
+        );
+
+        let ids: Vec<String> = ["Crypto", "Business", "Taxes", "Family"]
+            .iter()
+            .map(|name| format!("vault-{name}"))
+            .collect();
+        service.reorder(&all, &ids).unwrap();
+        service.set_favorite(&all, "vault-Family", true).unwrap();
+        service.set_favorite(&all, "vault-Taxes", true).unwrap();
+
+        let mut listed = all.clone();
+        service.apply(&mut listed).unwrap();
+        assert_eq!(
+            names(&listed),
+            vec!["Taxes", "Family", "Crypto", "Business"]
+        );
+        assert!(listed[0].favorite && listed[1].favorite && !listed[2].favorite);
+        assert_eq!(listed[2].sort_index, 0);
+
+        // Equal positions fall back to the name
+        let (a, mut b) = (listed[2].clone(), listed[3].clone());
+        b.sort_index = a.sort_index;
+        assert_eq!(compare_user_order(&b, &a), Ordering::Less);
+    }
+
+    #[test]
+    fn test_order_is_stable_after_a_deletion() {
+        let temp = TempDir::new().unwrap();
+        let service = VaultOrderService::at(temp.path().join("vault_order.json"));
+        let all = vaults(&["A", "B", "C", "D"]);
+        let ids: Vec<String> = ["D", "B", "C", "A"]
+            .iter()
+            .map(|name| format!("vault-{name}"))
+            .collect();
+        service.reorder(&all, &ids).unwrap();
+
+        // "B" deleted, "E" created afterwards
+        let mut remaining: Vec<VaultSummary> =
+            all.into_iter().filter(|vault| vault.name != "B").collect();
+        remaining.extend(vaults(&["E"]).into_iter().map(|mut vault| {
+            vault.created_at += Duration::days(1);
+            vault
+        }));
+        service.apply(&mut remaining).unwrap();
+        assert_eq!(names(&remaining), vec!["D", "C", "A", "E"]);
+        let indices: Vec<u32> = remaining.iter().map(|vault| vault.sort_index).collect();
+        assert_eq!(indices, vec![0, 1, 2, 3]);
+
+        // The stale order that still has "B" is refused
+        let err = service.reorder(&remaining, &ids).unwrap_err();
+        assert!(matches!(err, VaultError::InvalidOperation(_)));
+    }
+}
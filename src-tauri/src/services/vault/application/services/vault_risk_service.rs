@@ -0,0 +1,397 @@
+//! Vault Risk Service
+//!
+//! Classifies where each of a vault's keys physically lives and applies the
+//! key storage risk rules. Assessments are cached per vault behind a
+//! fingerprint of the inputs that decide them (key set, lifecycle, key file
+//! location), so relinking a key file or adding or removing a key is picked
+//! up on the next call without explicit invalidation.
+
+use crate::prelude::*;
+use crate::services::key_management::shared::{KeyEntry, KeyRegistry};
+use crate::services::shared::infrastructure::io::detect_cloud_sync;
+use crate::services::shared::infrastructure::path_management::get_keys_dir;
+use crate::services::vault::domain::models::{
+    KeyLocus, KeyStorage, RiskRule, VaultRiskAssessment, assess_key_storage,
+};
+use crate::services::vault::domain::{VaultError, VaultResult};
+use crate::services::vault::infrastructure::persistence::metadata::{
+    RecipientInfo, RecipientType, VaultMetadata,
+};
+use chrono::Utc;
+use std::collections::HashMap;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::path::Path;
+use std::sync::{Mutex, OnceLock};
+
+/// Last assessment per vault, with the fingerprint it was made for
+static ASSESSMENT_CACHE: OnceLock<Mutex<HashMap<String, (u64, VaultRiskAssessment)>>> =
+    OnceLock::new();
+
+/// Service for vault key storage risk assessment
+#[derive(Debug, Default)]
+pub struct VaultRiskService;
+
+impl VaultRiskService {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Assess a vault's key storage against the current key registry
+    pub fn assess(&self, metadata: &VaultMetadata) -> VaultResult<VaultRiskAssessment> {
+        let registry = KeyRegistry::load()
+            .map_err(|e| VaultError::StorageError(format!("Failed to load key registry: {e}")))?;
+        let keys_dir = get_keys_dir()
+            .map_err(|e| VaultError::StorageError(format!("Failed to locate keys: {e}")))?;
+        Ok(self.assess_with(metadata, &registry, &keys_dir))
+    }
+
+    /// Assess with a given registry, reusing the cached result while the
+    /// vault's keys are unchanged
+    pub fn assess_with(
+        &self,
+        metadata: &VaultMetadata,
+        registry: &KeyRegistry,
+        keys_dir: &Path,
+    ) -> VaultRiskAssessment {
+        let vault_id = metadata.vault_id().to_string();
+        let fingerprint = fingerprint(&vault_id, metadata.recipients(), registry);
+        let cache = ASSESSMENT_CACHE.get_or_init(|| Mutex::new(HashMap::new()));
+
+        if let Some((cached_fingerprint, assessment)) = cache
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(&vault_id)
+            && *cached_fingerprint == fingerprint
+        {
+            return assessment.clone();
+        }
+
+        let keys = classify_keys(metadata.recipients(), registry, keys_dir);
+        let findings = assess_key_storage(&keys);
+        let assessment = VaultRiskAssessment {
+            single_point_of_failure: findings
+                .iter()
+                .any(|f| f.rule == RiskRule::SinglePointOfFailure),
+            vault_id: vault_id.clone(),
+            keys,
+            findings,
+            assessed_at: Utc::now(),
+        };
+        debug!(
+            vault_id = %vault_id,
+            findings = assessment.findings.len(),
+            "Assessed vault key storage"
+        );
+
+        cache
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(vault_id, (fingerprint, assessment.clone()));
+        assessment
+    }
+}
+
+/// Classify the vault's usable keys; keys retired in the registry are left
+/// out since they no longer protect anything
+fn classify_keys(
+    recipients: &[RecipientInfo],
+    registry: &KeyRegistry,
+    keys_dir: &Path,
+) -> Vec<KeyStorage> {
+    recipients
+        .iter()
+        .filter_map(|recipient| {
+            let entry = registry.get_key(&recipient.key_id);
+            if entry.is_some_and(|e| !e.lifecycle_status().can_attach_to_vault()) {
+                return None;
+            }
+            Some(classify_key(recipient, entry, keys_dir))
+        })
+        .collect()
+}
+
+fn classify_key(
+    recipient: &RecipientInfo,
+    entry: Option<&KeyEntry>,
+    keys_dir: &Path,
+) -> KeyStorage {
+    let (locus, cloud_sync) = match entry {
+        Some(KeyEntry::Passphrase { key_location, .. }) => match key_location {
+            Some(location) => match &location.volume {
+                Some(volume) => (
+                    KeyLocus::RemovableVolume {
+                        label: volume.label.clone(),
+                    },
+                    None,
+                ),
+                None => (
+                    KeyLocus::ThisMachine,
+                    location
+                        .last_known_path
+                        .parent()
+                        .and_then(detect_cloud_sync),
+                ),
+            },
+            None => (KeyLocus::ThisMachine, detect_cloud_sync(keys_dir)),
+        },
+        Some(KeyEntry::Yubikey { serial, .. }) => (
+            KeyLocus::HardwareToken {
+                serial: serial.clone(),
+            },
+            None,
+        ),
+        Some(KeyEntry::Recipient { .. } | KeyEntry::YubikeyPending { .. }) => {
+            (KeyLocus::Unknown, None)
+        }
+        // Not in this device's registry: the manifest still tells a token apart
+        None => match &recipient.recipient_type {
+            RecipientType::YubiKey { serial, .. } => (
+                KeyLocus::HardwareToken {
+                    serial: serial.clone(),
+                },
+                None,
+            ),
+            _ => (KeyLocus::Unknown, None),
+        },
+    };
+
+    KeyStorage {
+        key_id: recipient.key_id.clone(),
+        label: entry
+            .map_or(recipient.label.as_str(), KeyEntry::label)
+            .to_string(),
+        locus,
+        cloud_sync,
+    }
+}
+
+/// Hash of everything the assessment depends on
+fn fingerprint(vault_id: &str, recipients: &[RecipientInfo], registry: &KeyRegistry) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    vault_id.hash(&mut hasher);
+    for recipient in recipients {
+        recipient.key_id.hash(&mut hasher);
+        match registry.get_key(&recipient.key_id) {
+            Some(entry) => {
+                format!("{:?}", entry.lifecycle_status()).hash(&mut hasher);
+                format!("{:?}", entry.key_location()).hash(&mut hasher);
+                entry.yubikey_serial().hash(&mut hasher);
+            }
+            None => "unregistered".hash(&mut hasher),
+        }
+    }
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::key_management::shared::KeyLifecycleStatus;
+    use crate::services::key_management::shared::domain::models::key_location::{
+        KeyFileLocation, KeyVolume,
+    };
+    use crate::services::shared::infrastructure::DeviceInfo;
+    use std::path::PathBuf;
+    use tempfile::TempDir;
+
+    fn passphrase(location: Option<KeyFileLocation>, status: KeyLifecycleStatus) -> KeyEntry {
+        KeyEntry::Passphrase {
+            label: "laptop-key".to_string(),
+            created_at: Utc::now(),
+            last_used: None,
+            public_key: "age1laptop".to_string(),
+            key_filename: "laptop-key.agekey.enc".to_string(),
+            lifecycle_status: status,
+            status_history: vec![],
+            vault_associations: vec![],
+            deactivated_at: None,
+            previous_lifecycle_status: None,
+            key_location: location,
+            passphrase_policy: 0,
+        }
+    }
+
+    fn yubikey(serial: &str) -> KeyEntry {
+        KeyEntry::Yubikey {
+            label: format!("yubikey-{serial}"),
+            created_at: Utc::now(),
+            last_used: None,
+            serial: serial.to_string(),
+            slot: 1,
+            piv_slot: 82,
+            recipient: "age1yubikey1test".to_string(),
+            identity_tag: "AGE-PLUGIN-YUBIKEY-TEST".to_string(),
+            model: "YubiKey 5C".to_string(),
+            firmware_version: None,
+            recovery_code_hash: String::new(),
+            lifecycle_status: KeyLifecycleStatus::Active,
+            status_history: vec![],
+            vault_associations: vec![],
+            deactivated_at: None,
+            previous_lifecycle_status: None,
+            last_reader: None,
+        }
+    }
+
+    fn on_usb(label: &str) -> KeyFileLocation {
+        KeyFileLocation {
+            volume: Some(KeyVolume {
+                uuid: None,
+                label: label.to_string(),
+                relative_path: PathBuf::from("keys/laptop-key.agekey.enc"),
+            }),
+            last_known_path: PathBuf::from("/media/KEYS/keys/laptop-key.agekey.enc"),
+        }
+    }
+
+    fn recipient(key_id: &str) -> RecipientInfo {
+        RecipientInfo::new_passphrase(
+            key_id.to_string(),
+            format!("age1{key_id}"),
+            key_id.to_string(),
+            format!("{key_id}.agekey.enc"),
+        )
+    }
+
+    fn metadata(vault_id: &str, recipients: Vec<RecipientInfo>) -> VaultMetadata {
+        let device = DeviceInfo {
+            machine_id: "test-machine".to_string(),
+            machine_label: "test-laptop".to_string(),
+            created_at: Utc::now(),
+            app_version: "2.0.0".to_string(),
+        };
+        VaultMetadata::new(
+            vault_id.to_string(),
+            "Family Docs".to_string(),
+            None,
+            "Family-Docs".to_string(),
+            &device,
+            None,
+            recipients,
+            vec![],
+            0,
+            0,
+        )
+    }
+
+    fn registry(entries: Vec<(&str, KeyEntry)>) -> KeyRegistry {
+        let mut registry = KeyRegistry::new();
+        for (key_id, entry) in entries {
+            registry.keys.insert(key_id.to_string(), entry);
+        }
+        registry
+    }
+
+    fn rules(assessment: &VaultRiskAssessment) -> Vec<RiskRule> {
+        assessment.findings.iter().map(|f| f.rule).collect()
+    }
+
+    #[test]
+    fn test_registry_states_classify_and_flag() {
+        use KeyLifecycleStatus::{Active, Compromised};
+        use RiskRule::*;
+
+        let plain = TempDir::new().unwrap();
+        let synced = TempDir::new().unwrap();
+        std::fs::create_dir(synced.path().join(".dropbox")).unwrap();
+
+        let cases: Vec<(&str, Vec<(&str, KeyEntry)>, &Path, Vec<RiskRule>)> = vec![
+            (
+                "two keys in the keys directory",
+                vec![
+                    ("a", passphrase(None, Active)),
+                    ("b", passphrase(None, Active)),
+                ],
+                plain.path(),
+                vec![SinglePointOfFailure, NoHardwareToken],
+            ),
+            (
+                "one key relinked to a USB stick",
+                vec![
+                    ("a", passphrase(None, Active)),
+                    ("b", passphrase(Some(on_usb("KEYS")), Active)),
+                ],
+                plain.path(),
+                vec![NoHardwareToken],
+            ),
+            (
+                "keys directory synced by Dropbox",
+                vec![("a", passphrase(None, Active)), ("b", yubikey("111"))],
+                synced.path(),
+                vec![KeyFileInCloudSync],
+            ),
+            (
+                "compromised key doesn't count",
+                vec![("a", passphrase(None, Compromised)), ("b", yubikey("111"))],
+                plain.path(),
+                vec![SinglePointOfFailure],
+            ),
+            (
+                "key missing from this device's registry",
+                vec![("a", passphrase(None, Active))],
+                plain.path(),
+                vec![NoHardwareToken],
+            ),
+        ];
+
+        for (index, (name, entries, keys_dir, expected)) in cases.into_iter().enumerate() {
+            let registry = registry(entries);
+            let metadata = metadata(
+                &format!("risk-table-{index}"),
+                vec![recipient("a"), recipient("b")],
+            );
+            let assessment = VaultRiskService::new().assess_with(&metadata, &registry, keys_dir);
+            assert_eq!(rules(&assessment), expected, "case: {}", name);
+            assert_eq!(
+                assessment.single_point_of_failure,
+                expected.contains(&SinglePointOfFailure),
+                "case: {}",
+                name
+            );
+        }
+    }
+
+    #[test]
+    fn test_relinking_refreshes_cached_assessment() {
+        let keys_dir = TempDir::new().unwrap();
+        let service = VaultRiskService::new();
+        let metadata = metadata("risk-relink", vec![recipient("a")]);
+
+        let mut registry = registry(vec![("a", passphrase(None, KeyLifecycleStatus::Active))]);
+        let before = service.assess_with(&metadata, &registry, keys_dir.path());
+        assert_eq!(before.keys[0].locus, KeyLocus::ThisMachine);
+        let cached = service.assess_with(&metadata, &registry, keys_dir.path());
+        assert_eq!(cached.assessed_at, before.assessed_at);
+
+        registry.relink_key_file("a", on_usb("KEYS")).unwrap();
+        let after = service.assess_with(&metadata, &registry, keys_dir.path());
+        assert_eq!(
+            after.keys[0].locus,
+            KeyLocus::RemovableVolume {
+                label: "KEYS".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn test_adding_a_key_refreshes_cached_assessment() {
+        let keys_dir = TempDir::new().unwrap();
+        let service = VaultRiskService::new();
+        let registry = registry(vec![
+            ("a", passphrase(None, KeyLifecycleStatus::Active)),
+            ("b", yubikey("111")),
+        ]);
+
+        let one_key = metadata("risk-add", vec![recipient("a")]);
+        assert!(
+            service
+                .assess_with(&one_key, &registry, keys_dir.path())
+                .single_point_of_failure
+        );
+
+        let two_keys = metadata("risk-add", vec![recipient("a"), recipient("b")]);
+        let assessment = service.assess_with(&two_keys, &registry, keys_dir.path());
+        assert!(!assessment.single_point_of_failure);
+        assert!(assessment.findings.is_empty());
+    }
+}
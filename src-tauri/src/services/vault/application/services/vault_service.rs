@@ -1,7 +1,10 @@
-use crate::services::shared::infrastructure::DeviceInfo;
-use crate::services::vault::application::services::VaultMetadataService;
+use crate::services::shared::infrastructure::{ChangeEvent, DeviceInfo, publish};
+use crate::services::vault::application::services::{
+    FileSearchService, ProtectionStatus, StatisticsHistoryService, VaultMetadataService,
+    VaultTemplateService,
+};
 use crate::services::vault::domain::models::VaultSummary;
-use crate::services::vault::domain::{VaultError, VaultResult, VaultRules};
+use crate::services::vault::domain::{NameKind, NameValidator, VaultError, VaultResult};
 use crate::services::vault::infrastructure::VaultRepository;
 use crate::services::vault::infrastructure::persistence::metadata::VaultMetadata;
 
@@ -9,6 +12,9 @@
 pub struct VaultService {
     repository: VaultRepository,
     metadata_service: VaultMetadataService,
+    template_service: VaultTemplateService,
+    file_search: FileSearchService,
+    statistics_history: StatisticsHistoryService,
 }
 
 impl VaultService {
@@ -16,6 +22,9 @@ pub fn new() -> Self {
         Self {
             repository: VaultRepository::new(),
             metadata_service: VaultMetadataService::new(),
+            template_service: VaultTemplateService::new(),
+            file_search: FileSearchService::new(),
+            statistics_history: StatisticsHistoryService::new(),
         }
     }
 
@@ -24,9 +33,20 @@ pub async fn create_vault(
         &self,
         name: String,
         description: Option<String>,
+        template_id: Option<String>,
     ) -> VaultResult<VaultSummary> {
-        // Apply domain rules
-        VaultRules::validate_vault_name(&name)?;
+        // Apply domain rules; existing names are only compared, never re-checked
+        let existing = self.repository.list_vaults().await?;
+        let name = NameValidator::validate_new(
+            NameKind::VaultName,
+            &name,
+            existing.iter().map(|m| m.label()),
+        )?;
+
+        // Resolve template before touching storage so an unknown ID fails cleanly
+        if let Some(id) = template_id.as_deref() {
+            self.template_service.get_template(id)?;
+        }
 
         // Check if vault already exists
         if self.repository.vault_exists(&name).await? {
@@ -41,15 +61,23 @@ pub async fn create_vault(
         let vault_id = Self::generate_vault_id();
 
         // Use VaultMetadataService to create manifest with defaults
-        let metadata = self
+        let mut metadata = self
             .metadata_service
             .load_or_create(&vault_id, &name, description, &device_info)
             .map_err(|e| {
                 VaultError::StorageError(format!("Failed to create vault metadata: {}", e))
             })?;
 
+        if let Some(id) = template_id.as_deref() {
+            self.template_service.apply_template(&mut metadata, id)?;
+        }
+
         // Save via repository
         self.repository.save_vault(&metadata).await?;
+        publish(ChangeEvent::VaultCreated {
+            vault_id: vault_id.clone(),
+            name: metadata.label().to_string(),
+        });
 
         Ok(metadata.to_summary())
     }
@@ -60,6 +88,11 @@ pub async fn list_vaults(&self) -> VaultResult<Vec<VaultSummary>> {
         Ok(metadatas.into_iter().map(|m| m.to_summary()).collect())
     }
 
+    /// List all vaults with their full metadata
+    pub async fn list_vault_metadata(&self) -> VaultResult<Vec<VaultMetadata>> {
+        self.repository.list_vaults().await
+    }
+
     /// Get vault metadata by ID
     pub async fn get_vault(&self, vault_id: &str) -> VaultResult<VaultMetadata> {
         self.repository.get_vault(vault_id).await
@@ -93,6 +126,12 @@ pub async fn get_vault_by_sanitized_name(
         Ok(None)
     }
 
+    /// Evaluate a vault's protection status against its template
+    pub async fn get_protection_status(&self, vault_id: &str) -> VaultResult<ProtectionStatus> {
+        let metadata = self.repository.get_vault(vault_id).await?;
+        Ok(self.template_service.protection_status(&metadata))
+    }
+
     /// Delete vault with optional force
     pub async fn delete_vault(&self, vault_id: &str, force: bool) -> VaultResult<()> {
         let metadata = self.repository.get_vault(vault_id).await?;
@@ -104,7 +143,13 @@ pub async fn delete_vault(&self, vault_id: &str, force: bool) -> VaultResult<()>
             ));
         }
 
-        self.repository.delete_vault(vault_id).await
+        self.repository.delete_vault(vault_id).await?;
+        self.file_search.remove_vault(vault_id);
+        self.statistics_history.remove_vault(vault_id);
+        publish(ChangeEvent::VaultDeleted {
+            vault_id: vault_id.to_string(),
+        });
+        Ok(())
     }
 
     /// Generate a unique vault ID
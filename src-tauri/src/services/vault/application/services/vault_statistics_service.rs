@@ -1,13 +1,21 @@
 //! Vault Statistics Service
 //!
 //! Aggregates vault statistics from manifest and key registry for the R2 UI.
-//! Provides real-time data about vault usage, key status, and encryption history.
+//! Provides real-time data about vault usage, key status, and encryption history,
+//! plus growth figures derived from the vault's statistics history.
 
 use crate::prelude::*;
 use crate::services::key_management::shared::KeyRegistryService;
 use crate::services::key_management::shared::domain::models::key_lifecycle::KeyLifecycleStatus;
 use crate::services::shared::infrastructure::{get_vault_manifest_path, get_vaults_directory};
+use crate::services::vault::application::services::{
+    RetentionService, StatisticsHistoryService, VaultItemService,
+};
+use crate::services::vault::domain::models::{
+    RetentionSummary, StatisticsTrend, VaultItemStatistics,
+};
 use crate::services::vault::infrastructure::persistence::metadata::{RecipientType, VaultMetadata};
+use crate::types::{ByteSize, FormatHints};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -39,10 +47,20 @@ pub struct VaultStatistics {
     pub last_encrypted_at: Option<DateTime<Utc>>,
     pub last_encrypted_by: Option<String>,
     pub file_count: usize,
-    pub total_size_bytes: u64,
+    pub total_size_bytes: ByteSize,
     pub key_statistics: KeyStatistics,
     pub archive_exists: bool,
     pub manifest_exists: bool,
+    /// Inheritance checklist item completion
+    pub item_statistics: VaultItemStatistics,
+    /// Archives the vault's retention policy would prune
+    #[serde(default)]
+    pub retention: RetentionSummary,
+    /// Growth and average archive size from the statistics history
+    #[serde(default)]
+    pub trend: StatisticsTrend,
+    /// Display form of `total_size_bytes`
+    pub format_hints: Option<FormatHints>,
 }
 
 /// Key statistics for a vault
@@ -54,6 +72,9 @@ pub struct KeyStatistics {
     pub passphrase_keys: usize,
     pub yubikey_keys: usize,
     pub key_details: Vec<KeyDetail>,
+    /// Reserved YubiKey slots waiting for their hardware (not in the manifest)
+    #[serde(default)]
+    pub pending_yubikeys: Vec<KeyDetail>,
 }
 
 /// Detailed information about a key
@@ -61,7 +82,7 @@ pub struct KeyStatistics {
 pub struct KeyDetail {
     pub key_id: String,
     pub label: String,
-    pub key_type: String, // "passphrase" or "yubikey"
+    pub key_type: String, // "passphrase", "yubikey" or "yubikey_pending"
     pub lifecycle_status: KeyLifecycleStatus,
     pub created_at: DateTime<Utc>,
     pub last_used: Option<DateTime<Utc>>,
@@ -77,13 +98,18 @@ pub struct GlobalVaultStatistics {
     pub orphaned_vaults: usize,
     pub total_encryptions: u32,
     pub total_files: usize,
-    pub total_size_bytes: u64,
+    pub total_size_bytes: ByteSize,
     pub vault_statistics: Vec<VaultStatistics>,
+    /// Display form of `total_size_bytes`
+    pub format_hints: Option<FormatHints>,
 }
 
 /// Service for aggregating vault statistics
 pub struct VaultStatisticsService {
     key_registry: KeyRegistryService,
+    item_service: VaultItemService,
+    retention_service: RetentionService,
+    history_service: StatisticsHistoryService,
 }
 
 impl VaultStatisticsService {
@@ -91,6 +117,9 @@ impl VaultStatisticsService {
     pub fn new() -> Self {
         Self {
             key_registry: KeyRegistryService::new(),
+            item_service: VaultItemService::new(),
+            retention_service: RetentionService::new(),
+            history_service: StatisticsHistoryService::new(),
         }
     }
 
@@ -195,7 +224,7 @@ pub fn get_all_vault_statistics(
             .count();
         let total_encryptions: u32 = vault_statistics.iter().map(|v| v.encryption_count).sum();
         let total_files: usize = vault_statistics.iter().map(|v| v.file_count).sum();
-        let total_size_bytes: u64 = vault_statistics.iter().map(|v| v.total_size_bytes).sum();
+        let total_size_bytes: ByteSize = vault_statistics.iter().map(|v| v.total_size_bytes).sum();
 
         Ok(GlobalVaultStatistics {
             total_vaults,
@@ -206,6 +235,7 @@ pub fn get_all_vault_statistics(
             total_files,
             total_size_bytes,
             vault_statistics,
+            format_hints: Some(FormatHints::size(total_size_bytes)),
         })
     }
 
@@ -218,6 +248,15 @@ fn build_statistics_from_manifest(
         manifest_exists: bool,
     ) -> Result<VaultStatistics, Box<dyn std::error::Error + Send + Sync>> {
         let key_statistics = self.build_key_statistics(&manifest)?;
+        let item_statistics = self.item_service.statistics(&manifest);
+        let retention = self
+            .retention_service
+            .summary(manifest.vault_id())
+            .unwrap_or_else(|e| {
+                warn!(vault_id = %manifest.vault_id(), "Failed to evaluate retention: {}", e);
+                RetentionSummary::default()
+            });
+        let trend = self.history_service.trend(manifest.vault_id());
 
         Ok(VaultStatistics {
             vault_id: manifest.vault_id().to_string(),
@@ -231,10 +270,14 @@ fn build_statistics_from_manifest(
                 .last_encrypted_by()
                 .map(|by| by.machine_label.clone()),
             file_count: manifest.file_count(),
-            total_size_bytes: manifest.total_size(),
+            total_size_bytes: ByteSize(manifest.total_size()),
             key_statistics,
             archive_exists,
             manifest_exists,
+            item_statistics,
+            retention,
+            trend,
+            format_hints: Some(FormatHints::size(ByteSize(manifest.total_size()))),
         })
     }
 
@@ -250,11 +293,13 @@ fn build_minimal_statistics(
         let total_size_bytes = if archive_exists {
             let vaults_dir = get_vaults_directory()?;
             let archive_path = vaults_dir.join(format!("{}.age", vault_name));
-            std::fs::metadata(&archive_path)
-                .map(|m| m.len())
-                .unwrap_or(0)
+            ByteSize(
+                std::fs::metadata(&archive_path)
+                    .map(|m| m.len())
+                    .unwrap_or(0),
+            )
         } else {
-            0
+            ByteSize::ZERO
         };
 
         Ok(VaultStatistics {
@@ -275,9 +320,14 @@ fn build_minimal_statistics(
                 passphrase_keys: 0,
                 yubikey_keys: 0,
                 key_details: vec![],
+                pending_yubikeys: vec![],
             },
             archive_exists,
             manifest_exists,
+            item_statistics: VaultItemStatistics::default(),
+            retention: RetentionSummary::default(),
+            trend: StatisticsTrend::default(),
+            format_hints: Some(FormatHints::size(total_size_bytes)),
         })
     }
 
@@ -346,6 +396,20 @@ fn build_key_statistics(
             });
         }
 
+        let pending_yubikeys = registry
+            .pending_yubikeys(manifest.vault_id())
+            .into_iter()
+            .map(|(key_id, entry)| KeyDetail {
+                key_id: key_id.clone(),
+                label: entry.label().to_string(),
+                key_type: "yubikey_pending".to_string(),
+                lifecycle_status: entry.lifecycle_status(),
+                created_at: entry.created_at(),
+                last_used: None,
+                is_available: false,
+            })
+            .collect();
+
         Ok(KeyStatistics {
             total_keys: key_details.len(),
             active_keys,
@@ -353,6 +417,7 @@ fn build_key_statistics(
             passphrase_keys,
             yubikey_keys,
             key_details,
+            pending_yubikeys,
         })
     }
 
@@ -0,0 +1,512 @@
+//! Vault Template Service
+//!
+//! Loads the built-in template catalog and computes template-driven protection
+//! status. The catalog is embedded JSON (`vault_templates.json`) so adding a
+//! template never touches this logic.
+//!
+//! Reserved YubiKey slots live in the key registry rather than the manifest,
+//! so they are folded into the status separately; whether they hold back the
+//! policy is a local vault setting.
+
+use crate::prelude::*;
+use crate::services::key_management::shared::KeyRegistry;
+use crate::services::shared::infrastructure::ClockService;
+use crate::services::shared::infrastructure::formatting::{Locale, format_relative};
+use crate::services::vault::domain::models::{
+    AppliedTemplate, ChecklistProgress, DeadMansSwitchStatus, ProtectionPolicy, VaultTemplate,
+};
+use crate::services::vault::domain::{VaultError, VaultResult};
+use crate::services::vault::infrastructure::persistence::metadata::{RecipientType, VaultMetadata};
+use crate::services::vault::infrastructure::persistence::{
+    VaultSettingsRegistry, configured_locale,
+};
+use chrono::{DateTime, Utc};
+
+/// Embedded template catalog
+const TEMPLATE_CATALOG_JSON: &str = include_str!("vault_templates.json");
+
+#[derive(Debug, Deserialize)]
+struct TemplateCatalog {
+    templates: Vec<VaultTemplate>,
+}
+
+/// Template reference shown alongside protection status
+#[derive(Debug, Clone, Serialize, Deserialize, specta::Type)]
+pub struct AppliedTemplateSummary {
+    pub id: String,
+    pub version: u32,
+    pub name: String,
+    pub applied_at: DateTime<Utc>,
+}
+
+/// Vault protection status evaluated against its template (if any)
+#[derive(Debug, Clone, Serialize, Deserialize, specta::Type)]
+pub struct ProtectionStatus {
+    pub vault_id: String,
+    pub vault_name: String,
+    pub passphrase_keys: usize,
+    pub yubikey_keys: usize,
+    pub template: Option<AppliedTemplateSummary>,
+    pub policy: Option<ProtectionPolicy>,
+    /// True when the vault meets its template policy (or has no template)
+    pub policy_satisfied: bool,
+    /// Human-readable unmet requirements
+    pub missing_requirements: Vec<String>,
+    pub freshness_target_days: Option<u32>,
+    /// `None` when never encrypted or while the clock is unreliable
+    pub days_since_last_encryption: Option<i64>,
+    /// Last encryption for display, e.g. "3 days ago", in the configured
+    /// locale; `None` alongside `days_since_last_encryption`
+    pub last_encrypted_display: Option<String>,
+    /// True when the last encryption is older than the freshness target
+    pub is_stale: bool,
+    /// Freshness is unknown because the system clock is implausible
+    pub clock_unreliable: bool,
+    pub checklist: Option<ChecklistProgress>,
+    /// Check-in state, from the local vault settings (filled in by `VaultManager`)
+    pub dead_mans_switch: Option<DeadMansSwitchStatus>,
+    /// Labels of reserved YubiKey slots still waiting for their hardware
+    /// (filled in by `VaultManager`)
+    pub pending_yubikeys: Vec<String>,
+}
+
+impl ProtectionStatus {
+    /// Record reserved YubiKey slots; unless `allowed`, each one is an unmet
+    /// requirement until its YubiKey is registered
+    pub fn apply_pending_yubikeys(&mut self, labels: Vec<String>, allowed: bool) {
+        if !allowed && !labels.is_empty() {
+            self.missing_requirements.extend(
+                labels
+                    .iter()
+                    .map(|label| format!("Register the YubiKey reserved as '{}'", label)),
+            );
+            self.policy_satisfied = false;
+        }
+        self.pending_yubikeys = labels;
+    }
+}
+
+/// Service for the vault template catalog
+#[derive(Debug)]
+pub struct VaultTemplateService {
+    clock: ClockService,
+    locale: Locale,
+}
+
+impl VaultTemplateService {
+    pub fn new() -> Self {
+        Self::with_clock(ClockService::global().clone()).with_locale(configured_locale())
+    }
+
+    pub fn with_clock(clock: ClockService) -> Self {
+        Self {
+            clock,
+            locale: Locale::default(),
+        }
+    }
+
+    /// Format display fields in `locale`
+    pub fn with_locale(mut self, locale: Locale) -> Self {
+        self.locale = locale;
+        self
+    }
+
+    /// List all built-in templates
+    pub fn list_templates(&self) -> VaultResult<Vec<VaultTemplate>> {
+        let catalog: TemplateCatalog = serde_json::from_str(TEMPLATE_CATALOG_JSON)
+            .map_err(|e| VaultError::OperationFailed(format!("Invalid template catalog: {}", e)))?;
+        Ok(catalog.templates)
+    }
+
+    /// Get a template by ID
+    pub fn get_template(&self, template_id: &str) -> VaultResult<VaultTemplate> {
+        self.list_templates()?
+            .into_iter()
+            .find(|t| t.id == template_id)
+            .ok_or_else(|| VaultError::TemplateNotFound(template_id.to_string()))
+    }
+
+    /// Record a template snapshot in a vault manifest and pre-populate its
+    /// suggested items
+    pub fn apply_template(
+        &self,
+        metadata: &mut VaultMetadata,
+        template_id: &str,
+    ) -> VaultResult<()> {
+        let template = self.get_template(template_id)?;
+
+        info!(
+            vault = %metadata.label(),
+            template = %template.id,
+            version = template.version,
+            "Applying vault template"
+        );
+
+        let applied = template.to_applied();
+        metadata
+            .items
+            .extend(applied.suggested_items(applied.applied_at));
+        metadata.template = Some(applied);
+        Ok(())
+    }
+
+    /// Evaluate protection policy, freshness and checklist progress for a vault
+    pub fn protection_status(&self, metadata: &VaultMetadata) -> ProtectionStatus {
+        let passphrase_keys = metadata
+            .recipients()
+            .iter()
+            .filter(|r| matches!(r.recipient_type, RecipientType::Passphrase { .. }))
+            .count();
+        let yubikey_keys = metadata
+            .recipients()
+            .iter()
+            .filter(|r| matches!(r.recipient_type, RecipientType::YubiKey { .. }))
+            .count();
+
+        let clock_unreliable = !self.clock.is_reliable();
+        let days_since_last_encryption = metadata
+            .last_encrypted_at()
+            .and_then(|at| self.clock.days_since(at));
+        let last_encrypted_display = metadata
+            .last_encrypted_at()
+            .filter(|_| !clock_unreliable)
+            .map(|at| format_relative(at, self.clock.now(), self.locale));
+
+        let Some(applied) = metadata.template.as_ref() else {
+            return ProtectionStatus {
+                vault_id: metadata.vault_id().to_string(),
+                vault_name: metadata.label().to_string(),
+                passphrase_keys,
+                yubikey_keys,
+                template: None,
+                policy: None,
+                policy_satisfied: true,
+                missing_requirements: vec![],
+                freshness_target_days: None,
+                days_since_last_encryption,
+                last_encrypted_display,
+                is_stale: false,
+                clock_unreliable,
+                checklist: None,
+                dead_mans_switch: None,
+                pending_yubikeys: vec![],
+            };
+        };
+
+        let missing_requirements =
+            Self::missing_requirements(&applied.protection_policy, passphrase_keys, yubikey_keys);
+
+        // Never-encrypted vaults are not stale - they are simply not started yet
+        let is_stale = days_since_last_encryption
+            .is_some_and(|days| days > i64::from(applied.freshness_target_days));
+
+        let checklist =
+            applied.checklist_progress(metadata.content.files.iter().map(|f| f.path.as_str()));
+
+        ProtectionStatus {
+            vault_id: metadata.vault_id().to_string(),
+            vault_name: metadata.label().to_string(),
+            passphrase_keys,
+            yubikey_keys,
+            template: Some(self.summarize(applied)),
+            policy: Some(applied.protection_policy.clone()),
+            policy_satisfied: missing_requirements.is_empty(),
+            missing_requirements,
+            freshness_target_days: Some(applied.freshness_target_days),
+            days_since_last_encryption,
+            last_encrypted_display,
+            is_stale,
+            clock_unreliable,
+            checklist: Some(checklist),
+            dead_mans_switch: None,
+            pending_yubikeys: vec![],
+        }
+    }
+
+    /// Fold the vault's reserved YubiKey slots from the key registry into `status`
+    pub fn apply_pending_yubikeys(&self, status: &mut ProtectionStatus) -> VaultResult<()> {
+        let registry = KeyRegistry::load()
+            .map_err(|e| VaultError::StorageError(format!("Failed to load key registry: {e}")))?;
+        let settings = load_settings()?;
+
+        let labels = registry
+            .pending_yubikeys(&status.vault_id)
+            .into_iter()
+            .map(|(_, entry)| entry.label().to_string())
+            .collect();
+        status.apply_pending_yubikeys(
+            labels,
+            settings.get(&status.vault_id).allow_pending_yubikeys,
+        );
+        Ok(())
+    }
+
+    /// Choose whether reserved YubiKey slots hold back a vault's policy
+    pub fn set_allow_pending_yubikeys(&self, vault_id: &str, allowed: bool) -> VaultResult<()> {
+        let mut settings = load_settings()?;
+        settings.entry(vault_id).allow_pending_yubikeys = allowed;
+        settings
+            .save()
+            .map_err(|e| VaultError::StorageError(e.to_string()))?;
+
+        info!(vault_id, allowed, "Updated reserved YubiKey policy");
+        Ok(())
+    }
+
+    fn summarize(&self, applied: &AppliedTemplate) -> AppliedTemplateSummary {
+        let name = self
+            .get_template(&applied.id)
+            .map(|t| t.name)
+            .unwrap_or_else(|_| applied.id.clone());
+
+        AppliedTemplateSummary {
+            id: applied.id.clone(),
+            version: applied.version,
+            name,
+            applied_at: applied.applied_at,
+        }
+    }
+
+    fn missing_requirements(
+        policy: &ProtectionPolicy,
+        passphrase_keys: usize,
+        yubikey_keys: usize,
+    ) -> Vec<String> {
+        let mut missing = Vec::new();
+
+        if passphrase_keys < policy.min_passphrase_keys {
+            missing.push(format!(
+                "Add {} passphrase key(s)",
+                policy.min_passphrase_keys - passphrase_keys
+            ));
+        }
+
+        if yubikey_keys < policy.min_yubikey_keys {
+            missing.push(format!(
+                "Add {} YubiKey(s)",
+                policy.min_yubikey_keys - yubikey_keys
+            ));
+        }
+
+        missing
+    }
+}
+
+impl Default for VaultTemplateService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn load_settings() -> VaultResult<VaultSettingsRegistry> {
+    VaultSettingsRegistry::load().map_err(|e| VaultError::StorageError(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::file::infrastructure::file_operations::{HashAlgorithm, RawPath};
+    use crate::services::shared::infrastructure::DeviceInfo;
+    use crate::services::shared::infrastructure::clock::testing::{
+        FakeClock, service as clock_service,
+    };
+    use crate::services::vault::infrastructure::persistence::metadata::{
+        EncryptionInfo, LastEncryptedBy, RecipientInfo, VaultFileEntry,
+    };
+
+    fn create_test_metadata(recipients: Vec<RecipientInfo>) -> VaultMetadata {
+        let device_info = DeviceInfo {
+            machine_id: "test-machine".to_string(),
+            machine_label: "test".to_string(),
+            created_at: Utc::now(),
+            app_version: "2.0.0".to_string(),
+        };
+
+        VaultMetadata::new(
+            "vault-001".to_string(),
+            "Template Vault".to_string(),
+            None,
+            "Template-Vault".to_string(),
+            &device_info,
+            None,
+            recipients,
+            vec![VaultFileEntry {
+                path: "wallets/descriptor.txt".to_string(),
+                raw_path: RawPath::from_display("wallets/descriptor.txt"),
+                lossy_name: false,
+                size: 10,
+                sha256: "abc".to_string(),
+                hash_algorithm: HashAlgorithm::Sha256,
+                digest: None,
+                ownership: None,
+                content_type: None,
+                content_type_mismatch: false,
+                transform: None,
+            }],
+            1,
+            10,
+        )
+    }
+
+    fn passphrase_recipient() -> RecipientInfo {
+        RecipientInfo::new_passphrase(
+            "pass-key".to_string(),
+            "age1pass".to_string(),
+            "pass-key".to_string(),
+            "pass-key.agekey.enc".to_string(),
+        )
+    }
+
+    #[test]
+    fn test_catalog_parses_and_ids_are_unique() {
+        let templates = VaultTemplateService::new().list_templates().unwrap();
+        assert!(templates.len() >= 3);
+
+        let mut ids: Vec<_> = templates.iter().map(|t| t.id.as_str()).collect();
+        ids.sort();
+        ids.dedup();
+        assert_eq!(ids.len(), templates.len());
+    }
+
+    #[test]
+    fn test_unknown_template() {
+        let result = VaultTemplateService::new().get_template("does-not-exist");
+        assert!(matches!(result, Err(VaultError::TemplateNotFound(_))));
+    }
+
+    #[test]
+    fn test_apply_template_records_snapshot() {
+        let service = VaultTemplateService::new();
+        let mut metadata = create_test_metadata(vec![passphrase_recipient()]);
+
+        service
+            .apply_template(&mut metadata, "bitcoin-cold-storage")
+            .unwrap();
+
+        let applied = metadata.template.as_ref().unwrap();
+        assert_eq!(applied.id, "bitcoin-cold-storage");
+        assert_eq!(applied.version, 1);
+        assert!(!applied.checklist.is_empty());
+        assert_eq!(metadata.items.len(), applied.checklist.len());
+        assert!(metadata.items.iter().all(|item| !item.completed));
+    }
+
+    #[test]
+    fn test_protection_status_with_template() {
+        let service = VaultTemplateService::new();
+        let mut metadata = create_test_metadata(vec![passphrase_recipient()]);
+        service
+            .apply_template(&mut metadata, "bitcoin-cold-storage")
+            .unwrap();
+
+        let status = service.protection_status(&metadata);
+
+        assert!(!status.policy_satisfied);
+        assert_eq!(status.missing_requirements, vec!["Add 1 YubiKey(s)"]);
+        assert!(!status.is_stale);
+
+        let checklist = status.checklist.unwrap();
+        assert_eq!(checklist.completed, 1);
+        assert!(
+            checklist
+                .items
+                .iter()
+                .any(|i| i.id == "wallet-descriptors" && i.completed)
+        );
+    }
+
+    #[test]
+    fn test_protection_status_without_template() {
+        let status = VaultTemplateService::new().protection_status(&create_test_metadata(vec![]));
+        assert!(status.policy_satisfied);
+        assert!(status.template.is_none());
+        assert!(status.checklist.is_none());
+    }
+
+    #[test]
+    fn test_pending_yubikeys_hold_back_policy() {
+        let service = VaultTemplateService::new();
+        let mut metadata = create_test_metadata(vec![passphrase_recipient()]);
+        service
+            .apply_template(&mut metadata, "bitcoin-cold-storage")
+            .unwrap();
+        let pending = vec!["Backup YubiKey".to_string()];
+
+        let mut status = service.protection_status(&metadata);
+        status.apply_pending_yubikeys(pending.clone(), false);
+        assert!(!status.policy_satisfied);
+        assert_eq!(
+            status.missing_requirements,
+            vec![
+                "Add 1 YubiKey(s)",
+                "Register the YubiKey reserved as 'Backup YubiKey'"
+            ]
+        );
+
+        // A vault without a template is held back too, unless allowed
+        let metadata = create_test_metadata(vec![passphrase_recipient()]);
+        let mut status = service.protection_status(&metadata);
+        status.apply_pending_yubikeys(pending.clone(), false);
+        assert!(!status.policy_satisfied);
+
+        let mut status = service.protection_status(&metadata);
+        status.apply_pending_yubikeys(pending, true);
+        assert!(status.policy_satisfied);
+        assert!(status.missing_requirements.is_empty());
+        assert_eq!(status.pending_yubikeys, vec!["Backup YubiKey"]);
+    }
+
+    #[test]
+    fn test_freshness_unknown_on_bad_clock() {
+        let clock = FakeClock::at("2026-03-01T00:00:00Z");
+        let service = VaultTemplateService::with_clock(clock_service(&clock));
+        let mut metadata = create_test_metadata(vec![passphrase_recipient()]);
+        service
+            .apply_template(&mut metadata, "bitcoin-cold-storage")
+            .unwrap();
+        metadata.versioning.last_encrypted = Some(EncryptionInfo {
+            at: "2025-01-15T00:00:00Z".parse().unwrap(),
+            by: LastEncryptedBy {
+                machine_id: "m".to_string(),
+                machine_label: "m".to_string(),
+            },
+            clock_unreliable: false,
+        });
+
+        let status = service.protection_status(&metadata);
+        assert!(status.is_stale);
+        assert!(status.days_since_last_encryption.is_some());
+        assert_eq!(
+            status.last_encrypted_display.as_deref(),
+            Some("15 Jan 2025")
+        );
+        assert!(!status.clock_unreliable);
+
+        // A dead RTC battery: 1970 would otherwise read as "never stale"
+        clock.set_wall("1970-01-01T00:00:00Z");
+        let status = service.protection_status(&metadata);
+        assert!(!status.is_stale);
+        assert_eq!(status.days_since_last_encryption, None);
+        assert_eq!(status.last_encrypted_display, None);
+        assert!(status.clock_unreliable);
+    }
+
+    #[test]
+    fn test_template_schema_round_trip() {
+        let service = VaultTemplateService::new();
+        let mut metadata = create_test_metadata(vec![passphrase_recipient()]);
+        service
+            .apply_template(&mut metadata, "family-documents")
+            .unwrap();
+
+        let json = serde_json::to_string(&metadata).unwrap();
+        let restored: VaultMetadata = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.template, metadata.template);
+
+        // Manifests without a template still deserialize
+        let mut plain = serde_json::to_value(&metadata).unwrap();
+        plain.as_object_mut().unwrap().remove("template");
+        let restored: VaultMetadata = serde_json::from_value(plain).unwrap();
+        assert!(restored.template.is_none());
+    }
+}
@@ -7,7 +7,10 @@
 use crate::services::shared::infrastructure::{
     atomic_write_sync, generate_backup_timestamp, get_manifest_backup_path,
 };
+use crate::services::vault::domain::models::GenerationFile;
 use crate::services::vault::infrastructure::persistence::metadata::VaultMetadata;
+use crate::services::vault::infrastructure::persistence::vault_generation::manifest_stamp_at;
+use crate::services::vault::infrastructure::persistence::{GenerationLedger, signed_manifest_json};
 use std::path::Path;
 
 /// Result of version comparison
@@ -29,6 +32,17 @@ pub enum VersionComparisonResult {
     NoLocal,
 }
 
+/// A manifest field whose embedded and external values disagree
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, specta::Type)]
+pub struct ManifestDiscrepancy {
+    /// Manifest field name (e.g. "revision", "recipients")
+    pub field: String,
+    /// Value stored inside the encrypted archive
+    pub embedded: String,
+    /// Value in the local external manifest
+    pub external: String,
+}
+
 /// Version comparison service
 #[derive(Debug)]
 pub struct VersionComparisonService;
@@ -69,6 +83,80 @@ pub fn compare_manifests(
         }
     }
 
+    /// List fields that differ between an archive's embedded manifest and the
+    /// local external manifest
+    ///
+    /// Only fields that identify the vault or describe the archive contents are
+    /// compared; an empty result means the sidecar is in sync with the archive.
+    pub fn find_discrepancies(
+        embedded: &VaultMetadata,
+        external: &VaultMetadata,
+    ) -> Vec<ManifestDiscrepancy> {
+        let recipient_ids = |manifest: &VaultMetadata| {
+            let mut ids = manifest.get_key_ids();
+            ids.sort();
+            ids.join(", ")
+        };
+
+        let fields = [
+            (
+                "vault_id",
+                embedded.vault_id().to_string(),
+                external.vault_id().to_string(),
+            ),
+            (
+                "label",
+                embedded.label().to_string(),
+                external.label().to_string(),
+            ),
+            (
+                "sanitized_name",
+                embedded.vault.sanitized_name.clone(),
+                external.vault.sanitized_name.clone(),
+            ),
+            (
+                "revision",
+                embedded.encryption_revision().to_string(),
+                external.encryption_revision().to_string(),
+            ),
+            (
+                "last_encrypted_at",
+                format_timestamp(embedded.last_encrypted_at()),
+                format_timestamp(external.last_encrypted_at()),
+            ),
+            (
+                "recipients",
+                recipient_ids(embedded),
+                recipient_ids(external),
+            ),
+            (
+                "file_count",
+                embedded.file_count().to_string(),
+                external.file_count().to_string(),
+            ),
+            (
+                "total_bytes",
+                embedded.total_size().to_string(),
+                external.total_size().to_string(),
+            ),
+            (
+                "bundle_type",
+                format!("{:?}", embedded.bundle_type),
+                format!("{:?}", external.bundle_type),
+            ),
+        ];
+
+        fields
+            .into_iter()
+            .filter(|(_, embedded, external)| embedded != external)
+            .map(|(field, embedded, external)| ManifestDiscrepancy {
+                field: field.to_string(),
+                embedded,
+                external,
+            })
+            .collect()
+    }
+
     /// Resolve version conflict with "newer wins" strategy
     ///
     /// Handles backup creation and manifest replacement based on comparison result.
@@ -149,7 +237,7 @@ pub fn resolve_with_backup(
     }
 
     /// Backup local manifest and replace with bundle manifest
-    fn backup_and_replace(
+    pub fn backup_and_replace(
         bundle_manifest: &VaultMetadata,
         manifest_path: &Path,
     ) -> Result<(), StorageError> {
@@ -305,18 +393,40 @@ pub fn restore_from_backup(
     }
 
     /// Save manifest to disk using atomic write
+    ///
+    /// The manifest replaces the local one on purpose, so it's stamped as this
+    /// device's next generation even over another device's copy. Without a
+    /// generation ledger (e.g. the config directory is unavailable) it's
+    /// written as is.
     fn save_manifest(manifest: &VaultMetadata, path: &Path) -> Result<(), StorageError> {
-        let json = serde_json::to_string_pretty(manifest).map_err(|e| {
-            StorageError::SerializationFailed {
-                message: format!("Failed to serialize manifest: {}", e),
-            }
-        })?;
+        let ledger = GenerationLedger::load()
+            .inspect_err(|e| warn!(error = %e, "Writing manifest without a generation"))
+            .ok();
+        let mut manifest = manifest.clone();
+        if let Some(ledger) = &ledger {
+            manifest.generation_stamp = ledger.stamp_over(
+                manifest.vault_id(),
+                GenerationFile::Manifest,
+                &manifest_stamp_at(path),
+            );
+        }
+        let json = signed_manifest_json(&manifest)?;
 
         atomic_write_sync(path, json.as_bytes()).map_err(|e| StorageError::FileWriteFailed {
             path: path.to_path_buf(),
             source: std::io::Error::other(e),
         })?;
 
+        if let Some(ledger) = &ledger
+            && let Err(e) = ledger.record(
+                manifest.vault_id(),
+                GenerationFile::Manifest,
+                manifest.generation_stamp.generation,
+            )
+        {
+            warn!(error = %e, "Failed to record manifest generation");
+        }
+
         Ok(())
     }
 
@@ -358,6 +468,12 @@ pub fn get_conflict_message(result: &VersionComparisonResult) -> Option<String>
     }
 }
 
+fn format_timestamp(timestamp: Option<chrono::DateTime<chrono::Utc>>) -> String {
+    timestamp
+        .map(|t| t.to_rfc3339())
+        .unwrap_or_else(|| "never".to_string())
+}
+
 impl Default for VersionComparisonService {
     fn default() -> Self {
         Self::new()
@@ -478,6 +594,54 @@ fn test_compare_no_local() {
         assert_eq!(result, VersionComparisonResult::NoLocal);
     }
 
+    #[test]
+    fn test_find_discrepancies_matching_manifests() {
+        let device_info = create_test_device_info();
+        let embedded = create_test_manifest(3, &device_info);
+        let external = embedded.clone();
+
+        assert!(VersionComparisonService::find_discrepancies(&embedded, &external).is_empty());
+    }
+
+    #[test]
+    fn test_find_discrepancies_stale_external() {
+        let device_info = create_test_device_info();
+        let external = create_test_manifest(1, &device_info);
+        let mut embedded = external.clone();
+        embedded.increment_version(&device_info);
+        embedded.add_recipient(RecipientInfo::new_passphrase(
+            "second-key".to_string(),
+            "age1second".to_string(),
+            "second-key".to_string(),
+            "second-key.agekey.enc".to_string(),
+        ));
+
+        let discrepancies = VersionComparisonService::find_discrepancies(&embedded, &external);
+        let fields: Vec<&str> = discrepancies.iter().map(|d| d.field.as_str()).collect();
+
+        assert_eq!(fields, vec!["revision", "last_encrypted_at", "recipients"]);
+        assert_eq!(discrepancies[0].embedded, "2");
+        assert_eq!(discrepancies[0].external, "1");
+        assert_eq!(discrepancies[2].embedded, "second-key, test-key");
+        assert_eq!(discrepancies[2].external, "test-key");
+    }
+
+    #[test]
+    fn test_find_discrepancies_different_vault() {
+        let device_info = create_test_device_info();
+        let embedded = create_test_manifest_with_name(1, &device_info, "Other-Vault");
+        let mut external = create_test_manifest(1, &device_info);
+        external.vault.id = "test-vault-002".to_string();
+
+        let fields: Vec<String> =
+            VersionComparisonService::find_discrepancies(&embedded, &external)
+                .into_iter()
+                .map(|d| d.field)
+                .collect();
+
+        assert_eq!(fields, vec!["vault_id", "sanitized_name"]);
+    }
+
     #[test]
     fn test_resolve_no_local() {
         let temp_dir = TempDir::new().unwrap();
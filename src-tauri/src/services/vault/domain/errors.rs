@@ -1,13 +1,49 @@
+use crate::services::shared::infrastructure::io::StoreLockError;
+use crate::services::vault::infrastructure::persistence::VaultConflictError;
+
 #[derive(Debug)]
 pub enum VaultError {
     NotFound(String),
     AlreadyExists(String),
     InvalidName(String),
+    /// A new name looks like an existing one without being identical
+    NameConfusable {
+        name: String,
+        conflicts_with: String,
+    },
     StorageError(String),
     KeyLimitExceeded(String),
     KeyNotFound(String),
     InvalidOperation(String),
     OperationFailed(String),
+    TemplateNotFound(String),
+    /// The archive is marked immutable and must be unlocked first
+    ArchiveImmutable {
+        archive_id: String,
+        archive_name: String,
+    },
+    /// The selection contains, or is inside, storage the app writes to
+    SelectionOverlapsAppData {
+        paths: Vec<String>,
+    },
+    /// Another vault already has an archive of this name in the output folder
+    CrossVaultNameCollision {
+        archive_name: String,
+        other_vault: String,
+    },
+    /// Another account holds the shared vault store's lock
+    StoreInUseBy {
+        user: String,
+        host: String,
+        /// The holder hasn't been seen lately and may be taken over
+        stale: bool,
+    },
+    /// Another device's copy of the vault's files replaced or sits beside
+    /// this device's; changes wait until the conflict is resolved
+    ConflictSuspected {
+        vault_id: String,
+        reason: String,
+    },
 }
 
 impl std::fmt::Display for VaultError {
@@ -16,15 +52,76 @@ fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
             Self::NotFound(id) => write!(f, "Vault '{}' not found", id),
             Self::AlreadyExists(name) => write!(f, "Vault '{}' already exists", name),
             Self::InvalidName(name) => write!(f, "Invalid vault name: '{}'", name),
+            Self::NameConfusable {
+                name,
+                conflicts_with,
+            } => write!(
+                f,
+                "'{}' is too easily confused with existing name '{}'",
+                name, conflicts_with
+            ),
             Self::StorageError(msg) => write!(f, "Storage error: {}", msg),
             Self::KeyLimitExceeded(vault) => write!(f, "Key limit exceeded for vault '{}'", vault),
             Self::KeyNotFound(key) => write!(f, "Key '{}' not found in vault", key),
             Self::InvalidOperation(msg) => write!(f, "Invalid operation: {}", msg),
             Self::OperationFailed(msg) => write!(f, "Operation failed: {}", msg),
+            Self::TemplateNotFound(id) => write!(f, "Vault template '{}' not found", id),
+            Self::ArchiveImmutable { archive_name, .. } => write!(
+                f,
+                "Archive '{}' is marked immutable and can't be changed or removed",
+                archive_name
+            ),
+            Self::SelectionOverlapsAppData { paths } => write!(
+                f,
+                "The selection includes Barqly Vault's own storage: {}",
+                paths.join(", ")
+            ),
+            Self::CrossVaultNameCollision {
+                archive_name,
+                other_vault,
+            } => write!(
+                f,
+                "'{}' in the output folder belongs to vault '{}'",
+                archive_name, other_vault
+            ),
+            Self::StoreInUseBy { user, host, stale } => write!(
+                f,
+                "The shared vault store is in use by {} on {}{}",
+                user,
+                host,
+                if *stale { " (not seen lately)" } else { "" }
+            ),
+            Self::ConflictSuspected { reason, .. } => write!(
+                f,
+                "{}. Resolve the conflict before changing the vault",
+                reason
+            ),
         }
     }
 }
 
 impl std::error::Error for VaultError {}
 
+impl From<StoreLockError> for VaultError {
+    fn from(e: StoreLockError) -> Self {
+        match e {
+            StoreLockError::InUse { owner, stale } => Self::StoreInUseBy {
+                user: owner.user,
+                host: owner.host,
+                stale,
+            },
+            e => Self::StorageError(e.to_string()),
+        }
+    }
+}
+
+impl From<VaultConflictError> for VaultError {
+    fn from(e: VaultConflictError) -> Self {
+        Self::ConflictSuspected {
+            reason: e.to_string(),
+            vault_id: e.vault_id,
+        }
+    }
+}
+
 pub type VaultResult<T> = std::result::Result<T, VaultError>;
@@ -0,0 +1,300 @@
+//! App version requirements for archives
+//!
+//! Each archive's manifest records two versions. `min_app_version` is the
+//! lowest app that understands everything in the archive. `required_app_version`
+//! is the lowest that can open it at all. Features an older app can safely
+//! ignore only raise the first, so an app between the two proceeds with a
+//! warning, while an app below the required version refuses the archive up
+//! front instead of failing halfway through.
+
+use crate::constants::{ARCHIVE_FEATURE_VERSIONS, BASELINE_APP_VERSION, VERSION};
+use std::fmt;
+
+/// An `X.Y.Z` app version
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct AppVersion {
+    pub major: u64,
+    pub minor: u64,
+    pub patch: u64,
+}
+
+impl AppVersion {
+    pub const fn new(major: u64, minor: u64, patch: u64) -> Self {
+        Self {
+            major,
+            minor,
+            patch,
+        }
+    }
+
+    /// Parse `X.Y.Z` (optionally `v`-prefixed), ignoring pre-release and
+    /// build suffixes
+    pub fn parse(text: &str) -> Option<Self> {
+        let core = text
+            .trim()
+            .trim_start_matches('v')
+            .split(['-', '+'])
+            .next()?;
+        let mut parts = core.split('.').map(|part| part.parse::<u64>().ok());
+        let version = Self::new(parts.next()??, parts.next()??, parts.next()??);
+        parts.next().is_none().then_some(version)
+    }
+
+    /// Version of this build
+    pub fn current() -> Self {
+        Self::parse(VERSION).expect("CARGO_PKG_VERSION is a semantic version")
+    }
+
+    /// Version required by archives using no optional features
+    pub fn baseline() -> Self {
+        Self::parse(BASELINE_APP_VERSION).expect("BASELINE_APP_VERSION is a semantic version")
+    }
+}
+
+impl fmt::Display for AppVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+/// Optional things an archive may contain that older apps don't know about
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ArchiveFeature {
+    /// Copy of the vault manifest inside the archive
+    EmbeddedManifest,
+    /// Owner names and IDs recorded per file
+    FileOwnership,
+    /// Source files left out because they couldn't be read
+    SkippedEntries,
+    /// Template snapshot applied at vault creation
+    VaultTemplate,
+    /// Structured inheritance checklist entries
+    VaultItems,
+    /// Directories recorded as entries, so empty ones are restored
+    DirectoryEntries,
+    /// Files verified with a hash other than SHA-256
+    FileDigests,
+    /// Names that aren't valid Unicode, stored escaped and restored exactly
+    RawFileNames,
+}
+
+impl ArchiveFeature {
+    pub const ALL: [Self; 8] = [
+        Self::EmbeddedManifest,
+        Self::FileOwnership,
+        Self::SkippedEntries,
+        Self::VaultTemplate,
+        Self::VaultItems,
+        Self::DirectoryEntries,
+        Self::FileDigests,
+        Self::RawFileNames,
+    ];
+
+    /// Key in `ARCHIVE_FEATURE_VERSIONS`
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::EmbeddedManifest => "embedded_manifest",
+            Self::FileOwnership => "file_ownership",
+            Self::SkippedEntries => "skipped_entries",
+            Self::VaultTemplate => "vault_template",
+            Self::VaultItems => "vault_items",
+            Self::DirectoryEntries => "directory_entries",
+            Self::FileDigests => "file_digests",
+            Self::RawFileNames => "raw_file_names",
+        }
+    }
+
+    /// Whether an app predating this feature can't open the archive at all
+    ///
+    /// Older apps ignore unknown manifest fields and extract the embedded
+    /// manifest as a plain file, so none of the current features are
+    /// required. Payload format changes (compression, chunking) would be.
+    pub fn is_required(self) -> bool {
+        match self {
+            Self::EmbeddedManifest
+            | Self::FileOwnership
+            | Self::SkippedEntries
+            | Self::VaultTemplate
+            | Self::VaultItems
+            | Self::DirectoryEntries
+            | Self::FileDigests
+            | Self::RawFileNames => false,
+        }
+    }
+
+    /// First app version that understands this feature
+    ///
+    /// A feature missing from the table is assumed to be as new as this build.
+    pub fn introduced_in(self) -> AppVersion {
+        ARCHIVE_FEATURE_VERSIONS
+            .iter()
+            .find(|(name, _)| *name == self.as_str())
+            .and_then(|(_, version)| AppVersion::parse(version))
+            .unwrap_or_else(AppVersion::current)
+    }
+}
+
+/// How this app relates to an archive's recorded requirements
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AppCompatibility {
+    Supported,
+    /// The archive opens, but some of its metadata is newer than this app
+    MissingOptionalFeatures {
+        recommended: AppVersion,
+    },
+    /// The archive can't be opened by this app
+    TooOld {
+        required: AppVersion,
+    },
+}
+
+/// Versions needed to fully understand and to open an archive
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AppRequirements {
+    /// Lowest version that understands every feature used
+    pub min: AppVersion,
+    /// Lowest version that can open the archive
+    pub required: AppVersion,
+}
+
+impl AppRequirements {
+    /// Requirements for an archive using `features`
+    pub fn for_features(features: &[ArchiveFeature]) -> Self {
+        let baseline = AppVersion::baseline();
+        let newest = |required_only: bool| {
+            features
+                .iter()
+                .filter(|feature| !required_only || feature.is_required())
+                .map(|feature| feature.introduced_in())
+                .fold(baseline, Ord::max)
+        };
+
+        Self {
+            min: newest(false),
+            required: newest(true),
+        }
+    }
+
+    /// Requirements recorded in a manifest
+    ///
+    /// Manifests written before these fields existed need only the baseline.
+    /// Unparseable values are ignored rather than locking the archive away.
+    pub fn from_recorded(min: Option<&str>, required: Option<&str>) -> Self {
+        let baseline = AppVersion::baseline();
+        let required = required.and_then(AppVersion::parse).unwrap_or(baseline);
+        let min = min
+            .and_then(AppVersion::parse)
+            .unwrap_or(baseline)
+            .max(required);
+
+        Self { min, required }
+    }
+
+    pub fn check(&self, current: AppVersion) -> AppCompatibility {
+        if current < self.required {
+            AppCompatibility::TooOld {
+                required: self.required,
+            }
+        } else if current < self.min {
+            AppCompatibility::MissingOptionalFeatures {
+                recommended: self.min,
+            }
+        } else {
+            AppCompatibility::Supported
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_every_feature_maps_to_a_version() {
+        for feature in ArchiveFeature::ALL {
+            let entry = ARCHIVE_FEATURE_VERSIONS
+                .iter()
+                .find(|(name, _)| *name == feature.as_str());
+            let version = entry.and_then(|(_, version)| AppVersion::parse(version));
+
+            assert!(version.is_some(), "{feature:?} has no version entry");
+            assert!(version.unwrap() >= AppVersion::baseline());
+            assert!(
+                version.unwrap() <= AppVersion::current(),
+                "{feature:?} requires a version newer than this build"
+            );
+        }
+        assert_eq!(ARCHIVE_FEATURE_VERSIONS.len(), ArchiveFeature::ALL.len());
+    }
+
+    #[test]
+    fn test_no_features_requires_only_baseline() {
+        let requirements = AppRequirements::for_features(&[]);
+
+        assert_eq!(requirements.min, AppVersion::baseline());
+        assert_eq!(requirements.required, AppVersion::baseline());
+        assert_eq!(
+            requirements.check(AppVersion::current()),
+            AppCompatibility::Supported
+        );
+    }
+
+    #[test]
+    fn test_optional_features_raise_only_min() {
+        let requirements = AppRequirements::for_features(&ArchiveFeature::ALL);
+
+        assert!(requirements.min >= AppVersion::baseline());
+        assert_eq!(requirements.required, AppVersion::baseline());
+        assert_eq!(
+            requirements.check(AppVersion::current()),
+            AppCompatibility::Supported
+        );
+    }
+
+    #[test]
+    fn test_check_against_recorded_versions() {
+        let requirements = AppRequirements::from_recorded(Some("0.5.0"), Some("0.4.1"));
+
+        assert_eq!(
+            requirements.check(AppVersion::new(0, 4, 0)),
+            AppCompatibility::TooOld {
+                required: AppVersion::new(0, 4, 1)
+            }
+        );
+        assert_eq!(
+            requirements.check(AppVersion::new(0, 4, 9)),
+            AppCompatibility::MissingOptionalFeatures {
+                recommended: AppVersion::new(0, 5, 0)
+            }
+        );
+        assert_eq!(
+            requirements.check(AppVersion::new(1, 0, 0)),
+            AppCompatibility::Supported
+        );
+    }
+
+    #[test]
+    fn test_recorded_versions_fall_back_to_baseline() {
+        let legacy = AppRequirements::from_recorded(None, None);
+        assert_eq!(legacy, AppRequirements::for_features(&[]));
+
+        let garbled = AppRequirements::from_recorded(Some("next"), Some("soon"));
+        assert_eq!(garbled, legacy);
+
+        // A required version above min lifts min with it
+        let inverted = AppRequirements::from_recorded(Some("0.2.0"), Some("0.3.0"));
+        assert_eq!(inverted.min, AppVersion::new(0, 3, 0));
+    }
+
+    #[test]
+    fn test_parse_versions() {
+        assert_eq!(AppVersion::parse("0.2.2"), Some(AppVersion::new(0, 2, 2)));
+        assert_eq!(
+            AppVersion::parse("v1.10.0-beta.1"),
+            Some(AppVersion::new(1, 10, 0))
+        );
+        assert_eq!(AppVersion::parse("1.2"), None);
+        assert_eq!(AppVersion::parse("1.2.3.4"), None);
+        assert!(AppVersion::new(0, 10, 0) > AppVersion::new(0, 9, 9));
+    }
+}
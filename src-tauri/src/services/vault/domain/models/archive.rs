@@ -0,0 +1,375 @@
+//! Archive index models
+//!
+//! One entry per encryption, carrying the optional user comment so past
+//! archives can be found without decrypting them. Comments are stored
+//! unencrypted, so they are length-capped and must not contain key material.
+
+use super::RetentionDecision;
+use crate::services::file::domain::models::{ByteRange, ParityCheck, ParityInfo};
+use crate::services::vault::domain::{VaultError, VaultResult};
+use crate::types::ByteSize;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Longest allowed archive comment, in characters
+pub const MAX_ARCHIVE_COMMENT_CHARS: usize = 280;
+
+/// Markers of secret material that must never be stored in a plaintext comment
+const SECRET_MARKERS: [&str; 2] = ["AGE-SECRET-KEY-", "AGE-PLUGIN-"];
+
+/// A single encryption recorded in the archive index
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+pub struct ArchiveIndexEntry {
+    pub archive_id: String,
+    pub vault_id: String,
+    /// File name of the encrypted bundle (e.g. "Family-Documents.age")
+    pub archive_name: String,
+    pub encryption_revision: u32,
+    pub created_at: DateTime<Utc>,
+    pub file_count: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub comment: Option<String>,
+    /// When the comment was last edited after encryption
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub comment_updated_at: Option<DateTime<Utc>>,
+    /// Never written over or removed while set
+    #[serde(default)]
+    pub immutable: bool,
+    /// Parity sidecar written beside the archive, when requested
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parity: Option<ParityInfo>,
+    /// Archive this one was re-encrypted from by a parameter migration
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub migrated_from: Option<String>,
+    /// Replaced by a migration and marked eligible for pruning
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub prune_marked: bool,
+    /// Archive this one was rewritten from with its internal paths remapped
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub remapped_from: Option<String>,
+    /// SHA-256 of the archive file as written, to check it when merging
+    /// another device's index
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub archive_sha256: Option<String>,
+    /// Public keys the archive was encrypted to; empty for archives recorded
+    /// before recipients were kept, whose header has to be read instead
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub recipients: Vec<String>,
+}
+
+/// An archive as listed to the user
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, specta::Type)]
+pub struct ArchiveListing {
+    pub entry: ArchiveIndexEntry,
+    /// The archive file still holds this encryption (later ones replace it)
+    pub current: bool,
+    /// The OS is also blocking changes to the archive file
+    pub os_protected: bool,
+    /// What the vault's retention policy says about it, when listed with one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub retention: Option<RetentionDecision>,
+}
+
+/// How repairing an archive from its parity ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+#[serde(rename_all = "snake_case")]
+pub enum RepairOutcome {
+    /// Neither the archive nor its parity was damaged
+    Intact,
+    /// Every damaged block was rebuilt and written back
+    Repaired,
+    /// Some stretch lost more blocks than its parity covers; nothing was written
+    Unrepairable,
+}
+
+/// Result of repairing an archive from its parity sidecar
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, specta::Type)]
+pub struct ArchiveRepairReport {
+    pub archive_id: String,
+    pub outcome: RepairOutcome,
+    /// Damage found before repairing
+    pub check: ParityCheck,
+    /// Archive byte ranges rewritten with rebuilt data
+    pub repaired_ranges: Vec<ByteRange>,
+    /// Damaged parity blocks written again
+    pub parity_blocks_rebuilt: usize,
+}
+
+impl ArchiveRepairReport {
+    /// Report for a repair that wrote back everything `check` found, if it could
+    pub fn new(archive_id: &str, check: ParityCheck) -> Self {
+        let outcome = if check.is_intact() {
+            RepairOutcome::Intact
+        } else if check.is_repairable() {
+            RepairOutcome::Repaired
+        } else {
+            RepairOutcome::Unrepairable
+        };
+        let repaired = outcome == RepairOutcome::Repaired;
+        Self {
+            archive_id: archive_id.to_string(),
+            outcome,
+            repaired_ranges: if repaired {
+                check.damaged_ranges.clone()
+            } else {
+                Vec::new()
+            },
+            parity_blocks_rebuilt: if repaired {
+                check.damaged_parity_blocks
+            } else {
+                0
+            },
+            check,
+        }
+    }
+}
+
+/// Typed to confirm clearing an archive's immutable flag
+pub const CLEAR_IMMUTABLE_CONFIRMATION: &str = "UNLOCK";
+
+/// Typed to confirm pruning archives
+pub const PRUNE_ARCHIVES_CONFIRMATION: &str = "PRUNE";
+
+/// An archive removed from the index by pruning
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, specta::Type)]
+pub struct PrunedArchive {
+    pub archive_id: String,
+    pub archive_name: String,
+    /// The archive file was deleted too (no remaining entry refers to it)
+    pub file_deleted: bool,
+}
+
+/// Result of pruning a vault's archives
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, specta::Type)]
+pub struct ArchivePruneReport {
+    pub pruned: Vec<PrunedArchive>,
+    /// Archive and parity files deleted
+    pub freed: ByteSize,
+}
+
+/// Which part of an entry matched a search query
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+#[serde(rename_all = "snake_case")]
+pub enum ArchiveMatchField {
+    Comment,
+    ArchiveName,
+    Date,
+}
+
+impl ArchiveMatchField {
+    /// Ranking weight: comments are the most specific signal
+    pub fn weight(&self) -> u32 {
+        match self {
+            Self::Comment => 3,
+            Self::ArchiveName => 2,
+            Self::Date => 1,
+        }
+    }
+}
+
+/// A ranked search result
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, specta::Type)]
+pub struct ArchiveSearchMatch {
+    pub entry: ArchiveIndexEntry,
+    pub matched_fields: Vec<ArchiveMatchField>,
+    pub score: u32,
+}
+
+/// Normalize and check a user-supplied archive comment
+///
+/// Blank comments become `None`. Over-long comments and comments that look
+/// like they contain a private key are rejected.
+pub fn validate_archive_comment(comment: Option<&str>) -> VaultResult<Option<String>> {
+    let Some(comment) = comment.map(str::trim).filter(|c| !c.is_empty()) else {
+        return Ok(None);
+    };
+
+    if comment.chars().count() > MAX_ARCHIVE_COMMENT_CHARS {
+        return Err(VaultError::InvalidOperation(format!(
+            "Archive comment must be at most {} characters",
+            MAX_ARCHIVE_COMMENT_CHARS
+        )));
+    }
+
+    if contains_secret_marker(comment) {
+        return Err(VaultError::InvalidOperation(
+            "Archive comments are stored unencrypted and must not contain key material".to_string(),
+        ));
+    }
+
+    if comment.chars().any(|c| c.is_control() && c != '\n') {
+        return Err(VaultError::InvalidOperation(
+            "Archive comment contains control characters".to_string(),
+        ));
+    }
+
+    Ok(Some(comment.to_string()))
+}
+
+/// True when `text` looks like it contains an age private key or plugin identity
+pub fn contains_secret_marker(text: &str) -> bool {
+    let upper = text.to_uppercase();
+    SECRET_MARKERS.iter().any(|marker| upper.contains(marker))
+}
+
+/// Match entries against a case-insensitive substring query
+///
+/// Highest score first; ties go to the newest archive.
+pub fn search_archive_entries(
+    entries: &[ArchiveIndexEntry],
+    query: &str,
+) -> Vec<ArchiveSearchMatch> {
+    let query = query.trim().to_lowercase();
+    if query.is_empty() {
+        return Vec::new();
+    }
+
+    let mut matches: Vec<ArchiveSearchMatch> = entries
+        .iter()
+        .filter_map(|entry| {
+            let mut matched_fields = Vec::new();
+            if entry
+                .comment
+                .as_deref()
+                .is_some_and(|c| c.to_lowercase().contains(&query))
+            {
+                matched_fields.push(ArchiveMatchField::Comment);
+            }
+            if entry.archive_name.to_lowercase().contains(&query) {
+                matched_fields.push(ArchiveMatchField::ArchiveName);
+            }
+            if archive_date_text(&entry.created_at).contains(&query) {
+                matched_fields.push(ArchiveMatchField::Date);
+            }
+
+            if matched_fields.is_empty() {
+                return None;
+            }
+            let score = matched_fields.iter().map(ArchiveMatchField::weight).sum();
+            Some(ArchiveSearchMatch {
+                entry: entry.clone(),
+                matched_fields,
+                score,
+            })
+        })
+        .collect();
+
+    matches.sort_by(|a, b| {
+        b.score
+            .cmp(&a.score)
+            .then_with(|| b.entry.created_at.cmp(&a.entry.created_at))
+    });
+    matches
+}
+
+/// Searchable date forms ("2026-03-14 09:30", "march 14 2026")
+fn archive_date_text(created_at: &DateTime<Utc>) -> String {
+    created_at
+        .format("%Y-%m-%d %H:%M %B %-d %Y")
+        .to_string()
+        .to_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn entry(name: &str, day: u32, comment: Option<&str>) -> ArchiveIndexEntry {
+        ArchiveIndexEntry {
+            archive_id: format!("archive-{day}"),
+            vault_id: "vault-001".to_string(),
+            archive_name: name.to_string(),
+            encryption_revision: day,
+            created_at: Utc.with_ymd_and_hms(2026, 3, day, 9, 30, 0).unwrap(),
+            file_count: 1,
+            comment: comment.map(str::to_string),
+            comment_updated_at: None,
+            immutable: false,
+            parity: None,
+            migrated_from: None,
+            prune_marked: false,
+            remapped_from: None,
+            archive_sha256: None,
+            recipients: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_search_ranks_comment_over_name_over_date() {
+        let entries = vec![
+            entry("Taxes.age", 1, None),
+            entry("Photos.age", 2, Some("Scanned TAX receipts")),
+            entry("Family.age", 3, Some("before the move")),
+        ];
+
+        let results = search_archive_entries(&entries, "tax");
+        let ids: Vec<_> = results
+            .iter()
+            .map(|m| m.entry.archive_id.as_str())
+            .collect();
+        assert_eq!(ids, vec!["archive-2", "archive-1"]);
+        assert_eq!(results[0].matched_fields, vec![ArchiveMatchField::Comment]);
+
+        let by_date = search_archive_entries(&entries, "2026-03-03");
+        assert_eq!(by_date.len(), 1);
+        assert_eq!(by_date[0].matched_fields, vec![ArchiveMatchField::Date]);
+
+        let by_month = search_archive_entries(&entries, "March");
+        let ids: Vec<_> = by_month
+            .iter()
+            .map(|m| m.entry.archive_id.as_str())
+            .collect();
+        assert_eq!(ids, vec!["archive-3", "archive-2", "archive-1"]);
+
+        assert!(search_archive_entries(&entries, "  ").is_empty());
+    }
+
+    #[test]
+    fn test_comment_cap_and_secret_check() {
+        assert_eq!(validate_archive_comment(None).unwrap(), None);
+        assert_eq!(validate_archive_comment(Some("   ")).unwrap(), None);
+        assert_eq!(
+            validate_archive_comment(Some(" q1 taxes ")).unwrap(),
+            Some("q1 taxes".to_string())
+        );
+
+        let at_cap = "é".repeat(MAX_ARCHIVE_COMMENT_CHARS);
+        assert!(validate_archive_comment(Some(&at_cap)).is_ok());
+        let over_cap = "a".repeat(MAX_ARCHIVE_COMMENT_CHARS + 1);
+        assert!(validate_archive_comment(Some(&over_cap)).is_err());
+
+        assert!(validate_archive_comment(Some("key age-secret-key-1qqq")).is_err());
+        assert!(validate_archive_comment(Some("AGE-PLUGIN-YUBIKEY-1ABC")).is_err());
+        assert!(validate_archive_comment(Some("tab\there")).is_err());
+    }
+
+    #[test]
+    fn test_repair_report_outcomes() {
+        let range = ByteRange {
+            offset: 0.into(),
+            length: 64.into(),
+        };
+        let intact = ArchiveRepairReport::new("a1", ParityCheck::default());
+        assert_eq!(intact.outcome, RepairOutcome::Intact);
+
+        let damaged = ParityCheck {
+            damaged_ranges: vec![range],
+            unrepairable_ranges: vec![],
+            damaged_parity_blocks: 1,
+        };
+        let repaired = ArchiveRepairReport::new("a1", damaged.clone());
+        assert_eq!(repaired.outcome, RepairOutcome::Repaired);
+        assert_eq!(repaired.repaired_ranges, vec![range]);
+        assert_eq!(repaired.parity_blocks_rebuilt, 1);
+
+        let lost = ParityCheck {
+            unrepairable_ranges: vec![range],
+            ..damaged
+        };
+        let unrepairable = ArchiveRepairReport::new("a1", lost);
+        assert_eq!(unrepairable.outcome, RepairOutcome::Unrepairable);
+        assert!(unrepairable.repaired_ranges.is_empty());
+        assert_eq!(unrepairable.parity_blocks_rebuilt, 0);
+    }
+}
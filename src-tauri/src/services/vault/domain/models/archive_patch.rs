@@ -0,0 +1,178 @@
+//! Archive patch models
+//!
+//! A patch replaces a few small files of an archive without re-encrypting
+//! it: a separate `.age` file holds only the replacement files and a patch
+//! manifest naming the base archive and the digests the replacements
+//! supersede. Patches stack. When files are materialized, the newest patch
+//! naming a path supplies it and the base archive supplies everything else.
+
+use crate::services::vault::domain::{VaultError, VaultResult};
+use crate::types::ByteSize;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Default limit on the replacement files of one patch, together
+pub const DEFAULT_PATCH_SIZE_CAP: ByteSize = ByteSize(10 * 1024 * 1024);
+
+/// Name of the patch manifest inside a patch payload
+pub const PATCH_MANIFEST_ENTRY: &str = ".barqly-patch.json";
+
+pub const PATCH_MANIFEST_SCHEMA: &str = "barqly.vault.archive-patch/1";
+
+/// A file to replace in an archive
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, specta::Type)]
+pub struct PatchReplacement {
+    /// Path as listed in the vault manifest
+    pub path: String,
+    /// File on this device holding the new content
+    pub source_file: String,
+}
+
+/// One replaced file in a patch
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+pub struct PatchedEntry {
+    /// Path as listed in the vault manifest
+    pub path: String,
+    /// Where the file sits inside the archive, and inside the patch
+    pub archived_path: String,
+    pub size: u64,
+    pub sha256: String,
+    /// Digest of the version replaced, from the base archive or an earlier patch
+    pub superseded_sha256: String,
+}
+
+/// Manifest carried inside a patch payload
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+pub struct PatchManifest {
+    pub schema: String,
+    pub patch_id: String,
+    pub vault_id: String,
+    pub base_archive_id: String,
+    /// Encryption revision of the base archive
+    pub base_revision: u32,
+    /// 1 for a base's first patch; later patches win
+    pub sequence: u32,
+    pub created_at: DateTime<Utc>,
+    pub entries: Vec<PatchedEntry>,
+}
+
+impl PatchManifest {
+    /// Replacement files together
+    pub fn total_size(&self) -> ByteSize {
+        ByteSize(self.entries.iter().map(|entry| entry.size).sum())
+    }
+}
+
+/// A patch recorded in the archive index
+///
+/// Keeps a copy of the patch's manifest, so a later patch knows the digests
+/// it supersedes without decrypting the earlier ones.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+pub struct ArchivePatchEntry {
+    /// File name of the patch, beside its base archive
+    pub patch_name: String,
+    /// SHA-256 of the patch file as written
+    pub archive_sha256: String,
+    pub manifest: PatchManifest,
+}
+
+/// File name of a base archive's patch, e.g. "Family.patch-2-1a2b3c4d.age"
+///
+/// The patch ID keeps patches of an earlier encryption of the same name
+/// from being written over.
+pub fn patch_file_name(archive_name: &str, sequence: u32, patch_id: &str) -> String {
+    let stem = archive_name.strip_suffix(".age").unwrap_or(archive_name);
+    let short_id: String = patch_id.chars().take(8).collect();
+    format!("{}.patch-{}-{}.age", stem, sequence, short_id)
+}
+
+/// Refuse replacement files larger than `cap` together
+pub fn check_patch_size(total: ByteSize, cap: ByteSize) -> VaultResult<()> {
+    if total > cap {
+        return Err(VaultError::InvalidOperation(format!(
+            "Replacement files total {}, more than the {} a patch may hold; \
+             encrypt the vault again instead",
+            total, cap
+        )));
+    }
+    Ok(())
+}
+
+/// The entry each patched path is materialized from: the newest patch's
+///
+/// Keyed by manifest path. Paths no patch names come from the base archive.
+pub fn resolve_overlay<'a>(
+    patches: impl IntoIterator<Item = &'a PatchManifest>,
+) -> BTreeMap<&'a str, (&'a PatchManifest, &'a PatchedEntry)> {
+    let mut newest_first: Vec<&PatchManifest> = patches.into_iter().collect();
+    newest_first.sort_by(|a, b| b.sequence.cmp(&a.sequence));
+
+    let mut overlay = BTreeMap::new();
+    for patch in newest_first {
+        for entry in &patch.entries {
+            overlay.entry(entry.path.as_str()).or_insert((patch, entry));
+        }
+    }
+    overlay
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn patch(sequence: u32, entries: &[(&str, &str)]) -> PatchManifest {
+        PatchManifest {
+            schema: PATCH_MANIFEST_SCHEMA.to_string(),
+            patch_id: format!("patch-{}", sequence),
+            vault_id: "vault-001".to_string(),
+            base_archive_id: "a1".to_string(),
+            base_revision: 3,
+            sequence,
+            created_at: Utc::now(),
+            entries: entries
+                .iter()
+                .map(|(path, sha256)| PatchedEntry {
+                    path: path.to_string(),
+                    archived_path: format!("Docs/{}", path),
+                    size: 2048,
+                    sha256: sha256.to_string(),
+                    superseded_sha256: "old".to_string(),
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_newest_of_two_stacked_patches_wins() {
+        let patches = [
+            patch(2, &[("descriptor.json", "second")]),
+            patch(1, &[("descriptor.json", "first"), ("notes.txt", "notes")]),
+        ];
+
+        let overlay = resolve_overlay(&patches);
+
+        assert_eq!(overlay.len(), 2);
+        let (layer, entry) = overlay["descriptor.json"];
+        assert_eq!(layer.sequence, 2);
+        assert_eq!(entry.sha256, "second");
+        assert_eq!(overlay["notes.txt"].0.sequence, 1);
+    }
+
+    #[test]
+    fn test_size_cap() {
+        let cap = DEFAULT_PATCH_SIZE_CAP;
+
+        assert!(check_patch_size(cap, cap).is_ok());
+        let err = check_patch_size(ByteSize(cap.bytes() + 1), cap).unwrap_err();
+        assert!(matches!(err, VaultError::InvalidOperation(_)));
+    }
+
+    #[test]
+    fn test_patch_file_name_sits_beside_the_base() {
+        assert_eq!(
+            patch_file_name("Family-Documents.age", 2, "1a2b3c4d-5e6f"),
+            "Family-Documents.patch-2-1a2b3c4d.age"
+        );
+    }
+}
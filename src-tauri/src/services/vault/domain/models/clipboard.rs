@@ -0,0 +1,142 @@
+//! Clipboard guard models
+//!
+//! Recipients, fingerprints, recovery codes and archive paths can be copied
+//! to the clipboard from their IDs, so the interface doesn't send the raw
+//! value back to copy it. Each copy is cleared after a while, but only while the clipboard
+//! still holds it, which is checked by hash. Copying is opt-in, and copying a
+//! recovery code also needs a typed confirmation.
+
+use crate::types::DurationMs;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+/// What the user must type to copy a recovery code
+pub const COPY_RECOVERY_CODE_CONFIRMATION: &str = "COPY";
+
+/// Time a copy stays on the clipboard when the settings don't say
+pub const DEFAULT_CLIPBOARD_CLEAR_AFTER: DurationMs = DurationMs(30_000);
+
+/// Shortest and longest time a copy may stay on the clipboard
+pub const MIN_CLIPBOARD_CLEAR_AFTER: DurationMs = DurationMs(5_000);
+pub const MAX_CLIPBOARD_CLEAR_AFTER: DurationMs = DurationMs(600_000);
+
+/// Kind of value copied
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+#[serde(rename_all = "snake_case")]
+pub enum ClipboardKind {
+    /// A key's or contact's age recipient
+    Recipient,
+    /// One share of a vault's recovery secret, while it is still held
+    RecoveryCode,
+    /// Short fingerprint of a key's or contact's recipient
+    Fingerprint,
+    /// Location of an archive file on this device
+    ArchivePath,
+}
+
+impl ClipboardKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Recipient => "recipient",
+            Self::RecoveryCode => "recovery code",
+            Self::Fingerprint => "fingerprint",
+            Self::ArchivePath => "archive path",
+        }
+    }
+
+    /// Copying it takes `COPY_RECOVERY_CODE_CONFIRMATION`
+    pub fn requires_confirmation(&self) -> bool {
+        matches!(self, Self::RecoveryCode)
+    }
+
+    /// Form of `value` fit for the operation log
+    ///
+    /// Recipients and fingerprints are public but shortened; recovery codes
+    /// and archive paths (which name the vault's files) are withheld.
+    pub fn redact(&self, value: &str) -> String {
+        let chars: Vec<char> = value.trim().chars().collect();
+        match self {
+            Self::Recipient if chars.len() > 16 => format!(
+                "{}…{}",
+                chars[..10].iter().collect::<String>(),
+                chars[chars.len() - 4..].iter().collect::<String>()
+            ),
+            Self::Fingerprint if chars.len() > 4 => {
+                format!("{}…", chars[..4].iter().collect::<String>())
+            }
+            _ => format!("[withheld, {} characters]", chars.len()),
+        }
+    }
+}
+
+/// Device-wide clipboard settings
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+#[serde(default)]
+pub struct ClipboardSettings {
+    /// Copying is off until the user turns it on
+    pub enabled: bool,
+    /// Time before a copy is cleared from the clipboard
+    pub clear_after: DurationMs,
+}
+
+impl Default for ClipboardSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            clear_after: DEFAULT_CLIPBOARD_CLEAR_AFTER,
+        }
+    }
+}
+
+/// A copy waiting to be cleared
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, specta::Type)]
+pub struct GuardedCopy {
+    pub kind: ClipboardKind,
+    pub copied_at: DateTime<Utc>,
+    pub clears_at: DateTime<Utc>,
+    /// Time left until it is cleared, for a countdown
+    pub remaining: DurationMs,
+}
+
+/// Clipboard settings and the copy waiting to be cleared, if any
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, specta::Type)]
+pub struct ClipboardGuardStatus {
+    pub settings: ClipboardSettings,
+    pub guarded: Option<GuardedCopy>,
+}
+
+/// SHA-256 of clipboard text, so the clipboard can be checked without
+/// keeping what another app put there
+pub fn clipboard_hash(text: &str) -> String {
+    hex::encode(Sha256::digest(text.as_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_redaction_keeps_secrets_out() {
+        let recipient = "age1ql3z7hjy54pw3hyww5ayyfg7zqgvc7w3j2elw8zmrj2kg5sfn9aqmcac8p";
+        let redacted = ClipboardKind::Recipient.redact(recipient);
+        assert_eq!(redacted, "age1ql3z7h…ac8p");
+
+        let code = "barqly-share-1-3-Zx8PqR2mT5vW9yB4";
+        let redacted = ClipboardKind::RecoveryCode.redact(code);
+        assert!(!redacted.contains("Zx8P"));
+        assert_eq!(redacted, "[withheld, 33 characters]");
+
+        let path = "/Users/sam/Barqly-Vaults/Family-Documents.age";
+        assert!(!ClipboardKind::ArchivePath.redact(path).contains("Family"));
+        assert_eq!(ClipboardKind::Fingerprint.redact("ab12-cd34"), "ab12…");
+    }
+
+    #[test]
+    fn test_only_recovery_codes_need_confirmation() {
+        assert!(ClipboardKind::RecoveryCode.requires_confirmation());
+        assert!(!ClipboardKind::Recipient.requires_confirmation());
+        assert!(!ClipboardKind::Fingerprint.requires_confirmation());
+        assert!(!ClipboardKind::ArchivePath.requires_confirmation());
+    }
+}
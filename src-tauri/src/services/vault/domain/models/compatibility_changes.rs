@@ -0,0 +1,278 @@
+//! Compatibility changelog
+//!
+//! Releases that change what ends up on disk (manifest fields, archive names,
+//! the key registry) are listed here, so after an upgrade the UI can explain
+//! why a first encryption looks different instead of leaving users to wonder
+//! whether their backups are broken. Summaries are localization keys; the
+//! catalog below holds the English text for each.
+
+use super::AppVersion;
+use serde::{Deserialize, Serialize};
+
+/// Part of the vault format a change affects
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize, specta::Type,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeArea {
+    ManifestSchema,
+    ArchiveNaming,
+    Compression,
+    KeyRegistry,
+}
+
+impl ChangeArea {
+    pub const ALL: [Self; 4] = [
+        Self::ManifestSchema,
+        Self::ArchiveNaming,
+        Self::Compression,
+        Self::KeyRegistry,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::ManifestSchema => "manifest_schema",
+            Self::ArchiveNaming => "archive_naming",
+            Self::Compression => "compression",
+            Self::KeyRegistry => "key_registry",
+        }
+    }
+
+    /// Localization key for the area's heading
+    pub fn message_key(self) -> String {
+        format!("compatibility.area.{}", self.as_str())
+    }
+}
+
+/// How noticeable a change is (ordered low to high)
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, specta::Type,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum ChangeImpact {
+    /// Internal only; nothing a user would notice
+    Low,
+    /// Visible in files or metadata, but needs no action
+    Medium,
+    /// Changes what users see on disk; worth explaining after an upgrade
+    High,
+}
+
+/// A changelog entry as embedded in the binary
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CompatibilityChange {
+    /// Release that introduced the change
+    pub version: &'static str,
+    pub area: ChangeArea,
+    pub summary_key: &'static str,
+    pub impact: ChangeImpact,
+}
+
+/// Vault-affecting changes, oldest first
+///
+/// Add an entry whenever a release changes manifest fields, archive naming,
+/// compression or the key registry format.
+pub const COMPATIBILITY_CHANGES: &[CompatibilityChange] = &[
+    CompatibilityChange {
+        version: "0.2.0",
+        area: ChangeArea::ManifestSchema,
+        summary_key: "compatibility.manifest_schema_v2",
+        impact: ChangeImpact::High,
+    },
+    CompatibilityChange {
+        version: "0.2.2",
+        area: ChangeArea::ManifestSchema,
+        summary_key: "compatibility.manifest_optional_fields",
+        impact: ChangeImpact::Medium,
+    },
+    CompatibilityChange {
+        version: "0.2.2",
+        area: ChangeArea::ArchiveNaming,
+        summary_key: "compatibility.shared_archive_copy",
+        impact: ChangeImpact::Medium,
+    },
+    CompatibilityChange {
+        version: "0.2.2",
+        area: ChangeArea::KeyRegistry,
+        summary_key: "compatibility.unique_key_labels",
+        impact: ChangeImpact::Low,
+    },
+];
+
+/// English text for every compatibility localization key
+pub const COMPATIBILITY_MESSAGES: &[(&str, &str)] = &[
+    ("compatibility.area.manifest_schema", "Vault manifest"),
+    ("compatibility.area.archive_naming", "Archive names"),
+    ("compatibility.area.compression", "Compression"),
+    ("compatibility.area.key_registry", "Key registry"),
+    (
+        "compatibility.manifest_schema_v2",
+        "Vault manifests use a new layout. Older archives still open normally.",
+    ),
+    (
+        "compatibility.manifest_optional_fields",
+        "Manifests now record file owners, skipped files, templates and vault items.",
+    ),
+    (
+        "compatibility.shared_archive_copy",
+        "A shareable copy of each archive is written alongside it.",
+    ),
+    (
+        "compatibility.unique_key_labels",
+        "Duplicate key labels are renamed so every key label is unique.",
+    ),
+];
+
+/// English text for a localization key
+pub fn default_message(key: &str) -> Option<&'static str> {
+    COMPATIBILITY_MESSAGES
+        .iter()
+        .find(|(candidate, _)| *candidate == key)
+        .map(|(_, text)| *text)
+}
+
+/// A changelog entry returned to the UI
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+pub struct CompatibilityChangeEntry {
+    pub version: String,
+    pub area: ChangeArea,
+    pub area_key: String,
+    pub summary_key: String,
+    /// English text, for when the UI has no translation
+    pub summary: String,
+    pub impact: ChangeImpact,
+}
+
+impl From<&CompatibilityChange> for CompatibilityChangeEntry {
+    fn from(change: &CompatibilityChange) -> Self {
+        Self {
+            version: change.version.to_string(),
+            area: change.area,
+            area_key: change.area.message_key(),
+            summary_key: change.summary_key.to_string(),
+            summary: default_message(change.summary_key)
+                .unwrap_or_default()
+                .to_string(),
+            impact: change.impact,
+        }
+    }
+}
+
+/// Compatibility changes between two app versions
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+pub struct CompatibilityReport {
+    /// Version changes are listed from (exclusive); `None` on a fresh install
+    pub since_version: Option<String>,
+    pub current_version: String,
+    pub changes: Vec<CompatibilityChangeEntry>,
+}
+
+impl CompatibilityReport {
+    pub fn new(since: Option<AppVersion>, current: AppVersion) -> Self {
+        Self {
+            since_version: since.map(|version| version.to_string()),
+            current_version: current.to_string(),
+            changes: changes_between(since, current),
+        }
+    }
+
+    /// Whether any change is worth explaining to the user
+    pub fn has_high_impact(&self) -> bool {
+        self.changes
+            .iter()
+            .any(|change| change.impact == ChangeImpact::High)
+    }
+}
+
+/// Changes after `since` up to and including `current`, oldest first
+///
+/// With no `since` (a fresh install) there is nothing to explain.
+pub fn changes_between(
+    since: Option<AppVersion>,
+    current: AppVersion,
+) -> Vec<CompatibilityChangeEntry> {
+    let Some(since) = since else {
+        return Vec::new();
+    };
+
+    COMPATIBILITY_CHANGES
+        .iter()
+        .filter(|change| {
+            AppVersion::parse(change.version)
+                .is_some_and(|version| version > since && version <= current)
+        })
+        .map(CompatibilityChangeEntry::from)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_changes_filtered_by_since_version() {
+        let current = AppVersion::new(0, 2, 2);
+
+        let all = changes_between(Some(AppVersion::new(0, 1, 0)), current);
+        assert_eq!(all.len(), COMPATIBILITY_CHANGES.len());
+
+        let since_020 = changes_between(Some(AppVersion::new(0, 2, 0)), current);
+        assert!(!since_020.is_empty());
+        assert!(since_020.iter().all(|change| change.version == "0.2.2"));
+
+        assert!(changes_between(Some(current), current).is_empty());
+        assert!(changes_between(None, current).is_empty());
+
+        // Entries newer than this build aren't reported yet
+        let older_build = changes_between(Some(AppVersion::new(0, 1, 0)), AppVersion::new(0, 2, 0));
+        assert!(older_build.iter().all(|change| change.version == "0.2.0"));
+    }
+
+    #[test]
+    fn test_report_flags_high_impact() {
+        let current = AppVersion::new(0, 2, 2);
+        assert!(
+            CompatibilityReport::new(Some(AppVersion::new(0, 1, 9)), current).has_high_impact()
+        );
+        assert!(
+            !CompatibilityReport::new(Some(AppVersion::new(0, 2, 0)), current).has_high_impact()
+        );
+    }
+
+    #[test]
+    fn test_every_entry_has_valid_version() {
+        for change in COMPATIBILITY_CHANGES {
+            let version = AppVersion::parse(change.version);
+            assert!(
+                version.is_some(),
+                "{} has no valid version",
+                change.summary_key
+            );
+            assert!(version.unwrap() <= AppVersion::current());
+        }
+        assert!(
+            COMPATIBILITY_CHANGES.windows(2).all(
+                |pair| AppVersion::parse(pair[0].version) <= AppVersion::parse(pair[1].version)
+            )
+        );
+    }
+
+    #[test]
+    fn test_every_key_is_in_the_catalog() {
+        for change in COMPATIBILITY_CHANGES {
+            assert!(
+                default_message(&change.area.message_key()).is_some(),
+                "{:?} has no catalog entry",
+                change.area
+            );
+            assert!(
+                default_message(change.summary_key).is_some(),
+                "{} has no catalog entry",
+                change.summary_key
+            );
+        }
+        for area in ChangeArea::ALL {
+            assert!(default_message(&area.message_key()).is_some());
+        }
+    }
+}
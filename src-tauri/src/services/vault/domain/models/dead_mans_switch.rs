@@ -0,0 +1,234 @@
+//! Dead man's switch models
+//!
+//! An optional check-in schedule supporting inheritance plans. The owner
+//! checks in at least every `check_in_interval_days`; once the interval
+//! lapses, the vault page shows the owner's `lapsed_message` to whoever opens
+//! it next. Everything stays on this device: nothing is sent anywhere and
+//! nothing is unlocked, the message is the whole mechanism.
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Typed to confirm a check-in
+pub const CHECK_IN_CONFIRMATION: &str = "CHECK IN";
+
+/// Longest allowed check-in interval (ten years)
+pub const MAX_CHECK_IN_INTERVAL_DAYS: u32 = 3650;
+
+/// Longest allowed lapsed message or translation, in characters
+pub const MAX_LAPSED_MESSAGE_CHARS: usize = 4000;
+
+/// Dead man's switch settings as entered by the owner
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+pub struct DeadMansSwitchInput {
+    pub check_in_interval_days: u32,
+    /// Days before the deadline that check-in reminders start
+    pub notify_before_days: u32,
+    /// Shown on the vault page once the interval lapses
+    pub lapsed_message: String,
+    /// `lapsed_message` in other languages, keyed by language code (e.g. "de")
+    #[serde(default)]
+    pub lapsed_message_translations: BTreeMap<String, String>,
+}
+
+impl DeadMansSwitchInput {
+    /// Refuse schedules that can't be followed and messages that can't be shown
+    pub fn validate(&self) -> Result<(), String> {
+        if self.check_in_interval_days == 0
+            || self.check_in_interval_days > MAX_CHECK_IN_INTERVAL_DAYS
+        {
+            return Err(format!(
+                "Check-in interval must be between 1 and {} days",
+                MAX_CHECK_IN_INTERVAL_DAYS
+            ));
+        }
+        if self.notify_before_days >= self.check_in_interval_days {
+            return Err(
+                "Reminders must start less than one check-in interval before the deadline"
+                    .to_string(),
+            );
+        }
+
+        validate_message(&self.lapsed_message)?;
+        for (language, message) in &self.lapsed_message_translations {
+            let valid_code = (2..=8).contains(&language.len())
+                && language.chars().all(|c| c.is_ascii_lowercase() || c == '-');
+            if !valid_code {
+                return Err(format!("'{}' is not a language code", language));
+            }
+            validate_message(message)?;
+        }
+        Ok(())
+    }
+}
+
+fn validate_message(message: &str) -> Result<(), String> {
+    if message.trim().is_empty() {
+        return Err("Lapsed message cannot be empty".to_string());
+    }
+    if message.chars().count() > MAX_LAPSED_MESSAGE_CHARS {
+        return Err(format!(
+            "Lapsed message is too long ({} characters max)",
+            MAX_LAPSED_MESSAGE_CHARS
+        ));
+    }
+    Ok(())
+}
+
+/// A vault's dead man's switch, as stored in the local vault settings
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeadMansSwitch {
+    pub check_in_interval_days: u32,
+    pub notify_before_days: u32,
+    pub lapsed_message: String,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub lapsed_message_translations: BTreeMap<String, String>,
+    pub last_check_in_at: DateTime<Utc>,
+}
+
+impl DeadMansSwitch {
+    /// A switch whose first interval starts at `checked_in_at`
+    pub fn new(input: DeadMansSwitchInput, checked_in_at: DateTime<Utc>) -> Self {
+        Self {
+            check_in_interval_days: input.check_in_interval_days,
+            notify_before_days: input.notify_before_days,
+            lapsed_message: input.lapsed_message,
+            lapsed_message_translations: input.lapsed_message_translations,
+            last_check_in_at: checked_in_at,
+        }
+    }
+
+    /// When the current interval lapses
+    pub fn due_at(&self) -> DateTime<Utc> {
+        self.last_check_in_at + Duration::days(i64::from(self.check_in_interval_days))
+    }
+
+    /// Where the switch stands at `now`; `None` while the clock is unreliable
+    pub fn status(&self, now: Option<DateTime<Utc>>) -> DeadMansSwitchStatus {
+        let due_at = self.due_at();
+        let state = match now {
+            None => CheckInState::Unknown,
+            Some(now) if now > due_at => CheckInState::Lapsed,
+            Some(now) if now >= due_at - Duration::days(i64::from(self.notify_before_days)) => {
+                CheckInState::DueSoon
+            }
+            Some(_) => CheckInState::Current,
+        };
+        let lapsed = state == CheckInState::Lapsed;
+
+        DeadMansSwitchStatus {
+            state,
+            check_in_interval_days: self.check_in_interval_days,
+            notify_before_days: self.notify_before_days,
+            last_check_in_at: self.last_check_in_at,
+            due_at,
+            days_until_due: now.filter(|_| !lapsed).map(|now| (due_at - now).num_days()),
+            days_overdue: now.filter(|_| lapsed).map(|now| (now - due_at).num_days()),
+            lapsed_message: lapsed.then(|| self.lapsed_message.clone()),
+            lapsed_message_translations: if lapsed {
+                self.lapsed_message_translations.clone()
+            } else {
+                BTreeMap::new()
+            },
+        }
+    }
+}
+
+/// Where a dead man's switch stands
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckInState {
+    Current,
+    /// Within `notify_before_days` of the deadline
+    DueSoon,
+    /// The interval passed without a check-in
+    Lapsed,
+    /// The system clock is implausible, so the deadline can't be judged
+    Unknown,
+}
+
+/// A dead man's switch evaluated for display
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+pub struct DeadMansSwitchStatus {
+    pub state: CheckInState,
+    pub check_in_interval_days: u32,
+    pub notify_before_days: u32,
+    pub last_check_in_at: DateTime<Utc>,
+    pub due_at: DateTime<Utc>,
+    /// Whole days left; `None` once lapsed or while the state is unknown
+    pub days_until_due: Option<i64>,
+    /// Whole days past the deadline; `None` unless lapsed
+    pub days_overdue: Option<i64>,
+    /// The owner's message, only once lapsed
+    pub lapsed_message: Option<String>,
+    /// Translations of the message by language code, only once lapsed
+    pub lapsed_message_translations: BTreeMap<String, String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn input() -> DeadMansSwitchInput {
+        DeadMansSwitchInput {
+            check_in_interval_days: 90,
+            notify_before_days: 14,
+            lapsed_message: "The recovery kit is in the safe deposit box.".to_string(),
+            lapsed_message_translations: BTreeMap::from([(
+                "de".to_string(),
+                "Das Wiederherstellungspaket liegt im Schließfach.".to_string(),
+            )]),
+        }
+    }
+
+    #[test]
+    fn test_validation() {
+        assert!(input().validate().is_ok());
+
+        let mut invalid = input();
+        invalid.check_in_interval_days = 0;
+        assert!(invalid.validate().is_err());
+
+        let mut invalid = input();
+        invalid.notify_before_days = 90;
+        assert!(invalid.validate().is_err());
+
+        let mut invalid = input();
+        invalid.lapsed_message = "  ".to_string();
+        assert!(invalid.validate().is_err());
+
+        let mut invalid = input();
+        invalid
+            .lapsed_message_translations
+            .insert("Deutsch".to_string(), "Hallo".to_string());
+        assert!(invalid.validate().is_err());
+    }
+
+    #[test]
+    fn test_message_is_revealed_only_once_lapsed() {
+        let checked_in: DateTime<Utc> = "2026-01-01T00:00:00Z".parse().unwrap();
+        let switch = DeadMansSwitch::new(input(), checked_in);
+        let at = |days: i64| Some(checked_in + Duration::days(days));
+
+        let status = switch.status(at(10));
+        assert_eq!(status.state, CheckInState::Current);
+        assert_eq!(status.days_until_due, Some(80));
+        assert_eq!(status.lapsed_message, None);
+        assert!(status.lapsed_message_translations.is_empty());
+
+        assert_eq!(switch.status(at(76)).state, CheckInState::DueSoon);
+
+        let status = switch.status(at(102));
+        assert_eq!(status.state, CheckInState::Lapsed);
+        assert_eq!(status.days_until_due, None);
+        assert_eq!(status.days_overdue, Some(12));
+        assert_eq!(status.lapsed_message, Some(input().lapsed_message));
+        assert_eq!(status.lapsed_message_translations.len(), 1);
+
+        let status = switch.status(None);
+        assert_eq!(status.state, CheckInState::Unknown);
+        assert_eq!(status.days_overdue, None);
+        assert_eq!(status.lapsed_message, None);
+    }
+}
@@ -0,0 +1,424 @@
+//! Directory comparison models
+//!
+//! Answers "is everything in this folder already backed up?" by matching a
+//! local directory's files against a vault manifest's file list. Paths are
+//! compared by `comparison_key`, so separator style and Unicode normalization
+//! (macOS stores NFD, most other systems NFC) don't produce false differences.
+//!
+//! A file a pre-encryption transform changed is recorded with its source's
+//! path and hash as well as the archived ones. The local file may be either:
+//! the source that was backed up, or the transformed file a restore wrote.
+
+use crate::types::ByteSize;
+use serde::{Deserialize, Serialize};
+use unicode_normalization::UnicodeNormalization;
+
+/// A file present on one side of the comparison
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+pub struct ComparedFile {
+    pub path: String,
+    pub size: ByteSize,
+}
+
+/// A file present on both sides whose content differs
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+pub struct ModifiedFile {
+    /// Path as found in the local directory
+    pub path: String,
+    pub local_size: ByteSize,
+    pub vault_size: ByteSize,
+}
+
+/// Counts and sizes per bucket
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+pub struct DirectoryComparisonSummary {
+    pub covered_count: usize,
+    pub covered_bytes: ByteSize,
+    pub modified_count: usize,
+    pub modified_bytes: ByteSize,
+    pub missing_from_vault_count: usize,
+    pub missing_from_vault_bytes: ByteSize,
+    pub missing_locally_count: usize,
+    pub missing_locally_bytes: ByteSize,
+    /// Local files skipped by exclusion patterns or as system files
+    pub excluded_count: usize,
+    pub unreadable_count: usize,
+}
+
+/// Result of comparing a local directory against a vault's manifest
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+pub struct DirectoryComparison {
+    pub vault_id: String,
+    /// Archive the file list belongs to, when one is recorded
+    pub archive_id: Option<String>,
+    pub encryption_revision: u32,
+    pub directory: String,
+    /// Identical content in the vault
+    pub covered: Vec<ComparedFile>,
+    /// Same path, different content
+    pub modified: Vec<ModifiedFile>,
+    /// Local files the vault doesn't contain
+    pub missing_from_vault: Vec<ComparedFile>,
+    /// Vault files no longer in the directory
+    pub missing_locally: Vec<ComparedFile>,
+    /// Local files that couldn't be hashed, with the reason
+    pub unreadable: Vec<UnreadableFile>,
+    pub summary: DirectoryComparisonSummary,
+    /// Directory-only differences; `None` when the manifest predates
+    /// directory entries
+    pub directories: Option<DirectoryDifferences>,
+    /// Every readable local file is covered by the vault
+    pub fully_covered: bool,
+}
+
+/// Directories present on one side only
+///
+/// A directory already accounted for by a file difference below it isn't
+/// listed again, so this holds what the file buckets can't show, chiefly
+/// empty directories.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+pub struct DirectoryDifferences {
+    /// Local directories the vault doesn't record
+    pub missing_from_vault: Vec<String>,
+    /// Recorded directories no longer in the local directory
+    pub missing_locally: Vec<String>,
+}
+
+impl DirectoryDifferences {
+    /// Match local directories against the manifest's, given the file buckets
+    pub fn compare(local: &[String], manifest: &[String], buckets: &ComparisonBuckets) -> Self {
+        let only_in = |side: &[String], other: &[String], files: &[ComparedFile]| {
+            let other: std::collections::HashSet<String> =
+                other.iter().map(|path| comparison_key(path)).collect();
+            let file_keys: Vec<String> = files.iter().map(|f| comparison_key(&f.path)).collect();
+
+            let mut paths: Vec<String> = side
+                .iter()
+                .filter(|path| {
+                    let key = comparison_key(path);
+                    let prefix = format!("{key}/");
+                    !other.contains(&key) && !file_keys.iter().any(|f| f.starts_with(&prefix))
+                })
+                .cloned()
+                .collect();
+            paths.sort();
+            paths
+        };
+
+        Self {
+            missing_from_vault: only_in(local, manifest, &buckets.missing_from_vault),
+            missing_locally: only_in(manifest, local, &buckets.missing_locally),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.missing_from_vault.is_empty() && self.missing_locally.is_empty()
+    }
+}
+
+/// A local file that couldn't be read for hashing
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+pub struct UnreadableFile {
+    pub path: String,
+    pub reason: String,
+}
+
+/// A hashed local file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LocalFileDigest {
+    pub path: String,
+    pub size: u64,
+    pub sha256: String,
+}
+
+/// A file as recorded in the vault manifest
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ManifestFileDigest {
+    pub path: String,
+    pub size: u64,
+    pub sha256: String,
+    /// The source a transform made the archived file from
+    pub original: Option<OriginalFileDigest>,
+}
+
+/// The source file of a transformed manifest entry
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OriginalFileDigest {
+    pub path: String,
+    pub size: u64,
+    pub sha256: String,
+}
+
+impl ManifestFileDigest {
+    /// Path the file had in the backed-up directory
+    fn source_path(&self) -> &str {
+        self.original.as_ref().map_or(&self.path, |o| &o.path)
+    }
+
+    /// Size the file had in the backed-up directory
+    fn source_size(&self) -> u64 {
+        self.original.as_ref().map_or(self.size, |o| o.size)
+    }
+
+    /// Whether `sha256` is the archived file's or its source's
+    fn matches_hash(&self, sha256: &str) -> bool {
+        self.sha256.eq_ignore_ascii_case(sha256)
+            || self
+                .original
+                .as_ref()
+                .is_some_and(|o| o.sha256.eq_ignore_ascii_case(sha256))
+    }
+}
+
+/// The buckets of a comparison, before vault details are attached
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ComparisonBuckets {
+    pub covered: Vec<ComparedFile>,
+    pub modified: Vec<ModifiedFile>,
+    pub missing_from_vault: Vec<ComparedFile>,
+    pub missing_locally: Vec<ComparedFile>,
+}
+
+impl ComparisonBuckets {
+    /// Sort local files into buckets by matching them against the manifest
+    ///
+    /// Results are sorted by path so repeated comparisons read the same.
+    ///
+    /// Transformed entries are keyed by their source's path, and also found
+    /// under their archived path when no local file has the source's name.
+    pub fn compare(local: &[LocalFileDigest], manifest: &[ManifestFileDigest]) -> Self {
+        let mut vault_files: std::collections::HashMap<String, &ManifestFileDigest> = manifest
+            .iter()
+            .map(|file| (comparison_key(file.source_path()), file))
+            .collect();
+        let archived_names: std::collections::HashMap<String, String> = manifest
+            .iter()
+            .filter(|file| file.source_path() != file.path)
+            .map(|file| {
+                (
+                    comparison_key(&file.path),
+                    comparison_key(file.source_path()),
+                )
+            })
+            .collect();
+        let local_keys: std::collections::HashSet<String> = local
+            .iter()
+            .map(|file| comparison_key(&file.path))
+            .collect();
+
+        let mut buckets = Self::default();
+        for file in local {
+            let key = comparison_key(&file.path);
+            let vault_file = vault_files.remove(&key).or_else(|| {
+                archived_names
+                    .get(&key)
+                    .filter(|source_key| !local_keys.contains(*source_key))
+                    .and_then(|source_key| vault_files.remove(source_key))
+            });
+            match vault_file {
+                Some(vault_file) if vault_file.matches_hash(&file.sha256) => {
+                    buckets.covered.push(ComparedFile {
+                        path: file.path.clone(),
+                        size: ByteSize(file.size),
+                    });
+                }
+                Some(vault_file) => buckets.modified.push(ModifiedFile {
+                    path: file.path.clone(),
+                    local_size: ByteSize(file.size),
+                    vault_size: ByteSize(vault_file.source_size()),
+                }),
+                None => buckets.missing_from_vault.push(ComparedFile {
+                    path: file.path.clone(),
+                    size: ByteSize(file.size),
+                }),
+            }
+        }
+
+        buckets.missing_locally = vault_files
+            .into_values()
+            .map(|file| ComparedFile {
+                path: file.source_path().to_string(),
+                size: ByteSize(file.source_size()),
+            })
+            .collect();
+
+        buckets.covered.sort_by(|a, b| a.path.cmp(&b.path));
+        buckets.modified.sort_by(|a, b| a.path.cmp(&b.path));
+        buckets
+            .missing_from_vault
+            .sort_by(|a, b| a.path.cmp(&b.path));
+        buckets.missing_locally.sort_by(|a, b| a.path.cmp(&b.path));
+        buckets
+    }
+
+    pub fn summary(
+        &self,
+        excluded_count: usize,
+        unreadable_count: usize,
+    ) -> DirectoryComparisonSummary {
+        fn total(files: &[ComparedFile]) -> ByteSize {
+            ByteSize(files.iter().map(|f| f.size.bytes()).sum())
+        }
+
+        DirectoryComparisonSummary {
+            covered_count: self.covered.len(),
+            covered_bytes: total(&self.covered),
+            modified_count: self.modified.len(),
+            modified_bytes: ByteSize(self.modified.iter().map(|f| f.local_size.bytes()).sum()),
+            missing_from_vault_count: self.missing_from_vault.len(),
+            missing_from_vault_bytes: total(&self.missing_from_vault),
+            missing_locally_count: self.missing_locally.len(),
+            missing_locally_bytes: total(&self.missing_locally),
+            excluded_count,
+            unreadable_count,
+        }
+    }
+}
+
+/// Key two relative paths are matched on
+///
+/// Uses `/` separators, drops a leading `./` or `/`, and normalizes to NFC.
+pub fn comparison_key(path: &str) -> String {
+    let normalized = path.replace('\\', "/");
+    let mut trimmed = normalized.as_str();
+    loop {
+        if let Some(rest) = trimmed.strip_prefix("./") {
+            trimmed = rest;
+        } else if let Some(rest) = trimmed.strip_prefix('/') {
+            trimmed = rest;
+        } else {
+            break;
+        }
+    }
+    trimmed.nfc().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn local(path: &str, size: u64, sha256: &str) -> LocalFileDigest {
+        LocalFileDigest {
+            path: path.to_string(),
+            size,
+            sha256: sha256.to_string(),
+        }
+    }
+
+    fn vault(path: &str, size: u64, sha256: &str) -> ManifestFileDigest {
+        ManifestFileDigest {
+            path: path.to_string(),
+            size,
+            sha256: sha256.to_string(),
+            original: None,
+        }
+    }
+
+    fn transformed(
+        path: &str,
+        size: u64,
+        sha256: &str,
+        original: (&str, u64, &str),
+    ) -> ManifestFileDigest {
+        ManifestFileDigest {
+            original: Some(OriginalFileDigest {
+                path: original.0.to_string(),
+                size: original.1,
+                sha256: original.2.to_string(),
+            }),
+            ..vault(path, size, sha256)
+        }
+    }
+
+    #[test]
+    fn test_comparison_key_normalizes_separators_and_unicode() {
+        assert_eq!(
+            comparison_key(r"taxes\2019\return.pdf"),
+            "taxes/2019/return.pdf"
+        );
+        assert_eq!(comparison_key("./notes.txt"), "notes.txt");
+        assert_eq!(
+            comparison_key("Cafe\u{301}.txt"),
+            comparison_key("Caf\u{e9}.txt")
+        );
+    }
+
+    #[test]
+    fn test_directory_differences_skip_directories_with_file_differences() {
+        let local_files = [local("new/file.txt", 1, "aaa")];
+        let manifest = [vault("gone/file.txt", 1, "bbb")];
+        let buckets = ComparisonBuckets::compare(&local_files, &manifest);
+
+        let strings = |paths: &[&str]| paths.iter().map(|p| p.to_string()).collect::<Vec<_>>();
+        let local_dirs = strings(&["kept", "new", "empty-local", r"kept\inner"]);
+        let vault_dirs = strings(&["kept", "gone", "empty-vault", "kept/inner"]);
+
+        let differences = DirectoryDifferences::compare(&local_dirs, &vault_dirs, &buckets);
+        assert_eq!(differences.missing_from_vault, ["empty-local"]);
+        assert_eq!(differences.missing_locally, ["empty-vault"]);
+    }
+
+    #[test]
+    fn test_compare_sorts_files_into_buckets() {
+        let local_files = [
+            local("same.txt", 3, "aaa"),
+            local(r"docs\changed.txt", 5, "bbb"),
+            local("new.txt", 7, "ccc"),
+        ];
+        let manifest = [
+            vault("same.txt", 3, "AAA"),
+            vault("docs/changed.txt", 4, "old"),
+            vault("gone.txt", 9, "ddd"),
+        ];
+
+        let buckets = ComparisonBuckets::compare(&local_files, &manifest);
+        assert_eq!(buckets.covered.len(), 1);
+        assert_eq!(buckets.modified[0].path, r"docs\changed.txt");
+        assert_eq!(buckets.modified[0].vault_size, ByteSize(4));
+        assert_eq!(buckets.missing_from_vault[0].path, "new.txt");
+        assert_eq!(buckets.missing_locally[0].path, "gone.txt");
+
+        let summary = buckets.summary(2, 0);
+        assert_eq!(summary.covered_bytes, ByteSize(3));
+        assert_eq!(summary.missing_locally_bytes, ByteSize(9));
+        assert_eq!(summary.excluded_count, 2);
+    }
+
+    #[test]
+    fn test_transformed_entries_match_source_or_restored_file() {
+        let manifest = [
+            transformed(
+                "scans/deed.png",
+                40,
+                "png",
+                ("scans/deed.tiff", 900, "tiff"),
+            ),
+            transformed("export.json", 10, "minified", ("export.json", 30, "pretty")),
+            transformed("photo.png", 5, "photo-png", ("photo.tif", 50, "photo-tif")),
+            transformed("notes.png", 5, "notes-png", ("notes.tif", 50, "notes-tif")),
+        ];
+
+        // The backed-up source, a restored copy, an edited source, and a
+        // transformed file no longer in the directory
+        let local_files = [
+            local("scans/deed.tiff", 900, "TIFF"),
+            local("export.json", 10, "minified"),
+            local("photo.tif", 51, "edited"),
+        ];
+        let buckets = ComparisonBuckets::compare(&local_files, &manifest);
+
+        let covered: Vec<&str> = buckets.covered.iter().map(|f| f.path.as_str()).collect();
+        assert_eq!(covered, ["export.json", "scans/deed.tiff"]);
+        assert_eq!(buckets.modified.len(), 1);
+        assert_eq!(buckets.modified[0].path, "photo.tif");
+        assert_eq!(buckets.modified[0].vault_size, ByteSize(50));
+        assert_eq!(buckets.missing_locally[0].path, "notes.tif");
+        assert_eq!(buckets.missing_locally[0].size, ByteSize(50));
+
+        // A restore wrote the archived name and content
+        let restored = [local("notes.png", 5, "notes-png")];
+        let buckets = ComparisonBuckets::compare(&restored, &manifest[3..]);
+        assert_eq!(buckets.covered[0].path, "notes.png");
+        assert!(buckets.missing_locally.is_empty());
+    }
+}
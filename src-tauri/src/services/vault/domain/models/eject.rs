@@ -0,0 +1,87 @@
+//! Eject safety models
+//!
+//! Archives written to a USB drive or SD card are flushed, and read back,
+//! before the app reports the drive safe to unplug. The files written beside
+//! an archive are flushed again when the user asks to eject, since some of
+//! them (upload checksums, parity) can be written after the archive.
+
+use crate::services::shared::infrastructure::OperationKind;
+use serde::Serialize;
+
+/// A file written beside an archive
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, specta::Type)]
+#[serde(rename_all = "snake_case")]
+pub enum SidecarKind {
+    /// `<archive>.manifest`
+    ExternalManifest,
+    /// `<vault>-RECOVERY.txt`
+    RecoveryInstructions,
+    /// `<archive>.upload.json`
+    UploadMetadata,
+    /// `<archive>.par`
+    Parity,
+}
+
+/// One sidecar flushed before ejecting
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, specta::Type)]
+pub struct SidecarFlush {
+    pub kind: SidecarKind,
+    pub path: String,
+    /// Why the flush failed; `None` when it reached the device
+    pub error: Option<String>,
+}
+
+/// Whether a removable volume can be unplugged
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, specta::Type)]
+pub struct EjectPreparation {
+    pub volume_name: String,
+    /// Archives on the volume that were flushed
+    pub archives_flushed: usize,
+    pub sidecars: Vec<SidecarFlush>,
+    /// Operations that may still be writing to the volume
+    pub running_operations: Vec<OperationKind>,
+    /// Flushes that failed other than for sidecars, e.g. of a folder
+    pub warnings: Vec<String>,
+    pub safe_to_eject: bool,
+}
+
+impl EjectPreparation {
+    /// Sidecars of `kind` that reached the device
+    pub fn flushed(&self, kind: SidecarKind) -> usize {
+        self.sidecars
+            .iter()
+            .filter(|sidecar| sidecar.kind == kind && sidecar.error.is_none())
+            .count()
+    }
+
+    /// Sidecars that couldn't be flushed
+    pub fn failed(&self) -> impl Iterator<Item = &SidecarFlush> {
+        self.sidecars
+            .iter()
+            .filter(|sidecar| sidecar.error.is_some())
+    }
+}
+
+/// How an encryption's bundles were finished on their volume
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EjectReadiness {
+    /// Removable volume the bundles were written to; `None` for fixed or
+    /// undetectable volumes, which need no extra finishing
+    pub volume_name: Option<String>,
+    pub safe_to_eject: bool,
+    /// Every bundle's read-back hash matched what was written
+    pub read_back_verified: bool,
+    pub warnings: Vec<String>,
+}
+
+impl EjectReadiness {
+    /// Bundles not on removable media
+    pub fn not_removable() -> Self {
+        Self {
+            volume_name: None,
+            safe_to_eject: true,
+            read_back_verified: false,
+            warnings: Vec::new(),
+        }
+    }
+}
@@ -0,0 +1,506 @@
+//! Cross-archive file search models
+//!
+//! The file search index holds one entry per archive with the file list from
+//! its manifest, so "which archive has tax-2019.pdf?" is answered without
+//! decrypting anything. Text is compared after lowercasing and Unicode NFC
+//! normalization, so a name stored decomposed (NFD, as macOS often writes
+//! it) matches a query typed composed.
+//!
+//! Queries are whitespace-separated terms, all of which must match:
+//! - plain words and `"quoted phrases"` match path components, content
+//!   types, and the archive comment
+//! - `ext:pdf` keeps files with that extension
+//! - `size>10mb`, `size<=500kb`, `size=0` compare the file size (units b,
+//!   kb, mb, gb, tb; 1 kb = 1024 bytes)
+
+use crate::services::vault::domain::{VaultError, VaultResult};
+use crate::types::ByteSize;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use unicode_normalization::UnicodeNormalization;
+
+/// Most file matches returned by one search
+pub const MAX_FILE_MATCHES: usize = 500;
+
+/// Vaults a file search covers
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+#[serde(tag = "mode", content = "vault_ids", rename_all = "snake_case")]
+pub enum FileSearchScope {
+    /// Every vault on this device
+    AllVaults,
+    /// Only these vaults
+    Selected(Vec<String>),
+}
+
+impl FileSearchScope {
+    pub fn includes(&self, vault_id: &str) -> bool {
+        match self {
+            Self::AllVaults => true,
+            Self::Selected(vault_ids) => vault_ids.iter().any(|id| id == vault_id),
+        }
+    }
+}
+
+/// A file as recorded in an archive's manifest
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IndexedFile {
+    /// Path relative to the archive root, '/'-separated
+    pub path: String,
+    pub size: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content_type: Option<String>,
+}
+
+/// An archive in the file search index
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IndexedArchive {
+    pub vault_id: String,
+    pub vault_name: String,
+    pub archive_id: String,
+    pub archive_name: String,
+    pub created_at: DateTime<Utc>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub comment: Option<String>,
+    /// Empty when the archive's file list is no longer known, e.g. an older
+    /// encryption indexed during a rebuild
+    #[serde(default)]
+    pub files: Vec<IndexedFile>,
+}
+
+/// Comparison in a `size` operator
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SizeComparison {
+    Greater,
+    GreaterOrEqual,
+    Less,
+    LessOrEqual,
+    Equal,
+}
+
+/// A `size>10mb` style filter
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SizeFilter {
+    pub comparison: SizeComparison,
+    pub bytes: u64,
+}
+
+impl SizeFilter {
+    pub fn accepts(&self, size: u64) -> bool {
+        match self.comparison {
+            SizeComparison::Greater => size > self.bytes,
+            SizeComparison::GreaterOrEqual => size >= self.bytes,
+            SizeComparison::Less => size < self.bytes,
+            SizeComparison::LessOrEqual => size <= self.bytes,
+            SizeComparison::Equal => size == self.bytes,
+        }
+    }
+}
+
+/// A parsed file search query
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FileQuery {
+    /// Normalized words and phrases; every one must match
+    pub terms: Vec<String>,
+    /// Normalized extensions without the leading dot; any may match
+    pub extensions: Vec<String>,
+    pub size_filters: Vec<SizeFilter>,
+}
+
+impl FileQuery {
+    /// Parse a query, rejecting malformed operators
+    pub fn parse(query: &str) -> VaultResult<Self> {
+        let mut parsed = Self::default();
+        let mut rest = query.trim();
+
+        while !rest.is_empty() {
+            if let Some(quoted) = rest.strip_prefix('"') {
+                let (phrase, tail) = quoted.split_once('"').unwrap_or((quoted, ""));
+                let phrase = normalize_search_text(phrase.trim());
+                if !phrase.is_empty() {
+                    parsed.terms.push(phrase);
+                }
+                rest = tail.trim_start();
+                continue;
+            }
+
+            let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+            parsed.push_word(&rest[..end])?;
+            rest = rest[end..].trim_start();
+        }
+
+        Ok(parsed)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.terms.is_empty() && self.extensions.is_empty() && self.size_filters.is_empty()
+    }
+
+    fn push_word(&mut self, word: &str) -> VaultResult<()> {
+        let lowered = word.to_lowercase();
+        if let Some(extension) = lowered.strip_prefix("ext:") {
+            let extension = normalize_search_text(extension.trim_start_matches('.'));
+            if extension.is_empty() {
+                return Err(invalid_operator(word, "ext:pdf"));
+            }
+            self.extensions.push(extension);
+        } else if let Some(filter) = lowered
+            .strip_prefix("size")
+            .filter(|rest| rest.starts_with(['>', '<', '=', ':']))
+        {
+            self.size_filters.push(
+                parse_size_filter(filter).ok_or_else(|| invalid_operator(word, "size>10mb"))?,
+            );
+        } else {
+            self.terms.push(normalize_search_text(word));
+        }
+        Ok(())
+    }
+
+    /// Whether `file` satisfies every part of the query
+    ///
+    /// `comment` is the archive's normalized comment.
+    fn matches(&self, file: &IndexedFile, comment: &str) -> bool {
+        let path = normalize_search_text(&file.path);
+        let content_type = file
+            .content_type
+            .as_deref()
+            .map(normalize_search_text)
+            .unwrap_or_default();
+
+        let terms_match = self.terms.iter().all(|term| {
+            path.contains(term.as_str())
+                || content_type.contains(term.as_str())
+                || comment.contains(term.as_str())
+        });
+        let extension_matches = self.extensions.is_empty()
+            || file_extension(&path).is_some_and(|ext| self.extensions.iter().any(|e| e == ext));
+        let size_matches = self.size_filters.iter().all(|f| f.accepts(file.size));
+
+        terms_match && extension_matches && size_matches
+    }
+}
+
+/// A matched file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, specta::Type)]
+pub struct FileMatch {
+    pub path: String,
+    pub file_name: String,
+    pub size: ByteSize,
+    pub content_type: Option<String>,
+}
+
+/// Matches within one archive
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, specta::Type)]
+pub struct ArchiveFileMatches {
+    pub archive_id: String,
+    pub archive_name: String,
+    pub created_at: DateTime<Utc>,
+    pub comment: Option<String>,
+    pub files: Vec<FileMatch>,
+}
+
+/// Matches within one vault, newest archive first
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, specta::Type)]
+pub struct VaultFileMatches {
+    pub vault_id: String,
+    pub vault_name: String,
+    pub archives: Vec<ArchiveFileMatches>,
+}
+
+/// Search results grouped by vault
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, specta::Type)]
+pub struct FileSearchResults {
+    /// Ordered by vault name
+    pub vaults: Vec<VaultFileMatches>,
+    pub total_matches: usize,
+    /// More than `MAX_FILE_MATCHES` files matched; the rest were dropped
+    pub truncated: bool,
+}
+
+/// Lowercase and NFC-normalize text for comparison
+pub fn normalize_search_text(text: &str) -> String {
+    text.to_lowercase().nfc().collect()
+}
+
+/// Match `query` against the files of indexed archives
+///
+/// An empty query matches nothing.
+pub fn search_indexed_files<'a>(
+    archives: impl IntoIterator<Item = &'a IndexedArchive>,
+    query: &FileQuery,
+) -> FileSearchResults {
+    let mut results = FileSearchResults::default();
+    if query.is_empty() {
+        return results;
+    }
+
+    let mut by_vault: BTreeMap<(String, String), Vec<ArchiveFileMatches>> = BTreeMap::new();
+    for archive in archives {
+        let comment = archive
+            .comment
+            .as_deref()
+            .map(normalize_search_text)
+            .unwrap_or_default();
+        let files: Vec<FileMatch> = archive
+            .files
+            .iter()
+            .filter(|file| query.matches(file, &comment))
+            .map(file_match)
+            .collect();
+        if files.is_empty() {
+            continue;
+        }
+
+        results.total_matches += files.len();
+        by_vault
+            .entry((archive.vault_name.clone(), archive.vault_id.clone()))
+            .or_default()
+            .push(ArchiveFileMatches {
+                archive_id: archive.archive_id.clone(),
+                archive_name: archive.archive_name.clone(),
+                created_at: archive.created_at,
+                comment: archive.comment.clone(),
+                files,
+            });
+    }
+
+    let mut remaining = MAX_FILE_MATCHES;
+    for ((vault_name, vault_id), mut archives) in by_vault {
+        if remaining == 0 {
+            break;
+        }
+        archives.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+        for archive in &mut archives {
+            archive.files.truncate(remaining);
+            remaining -= archive.files.len();
+        }
+        archives.retain(|archive| !archive.files.is_empty());
+        results.vaults.push(VaultFileMatches {
+            vault_id,
+            vault_name,
+            archives,
+        });
+    }
+    results.truncated = results.total_matches > MAX_FILE_MATCHES;
+    results
+}
+
+fn file_match(file: &IndexedFile) -> FileMatch {
+    FileMatch {
+        path: file.path.clone(),
+        file_name: file.path.rsplit('/').next().unwrap_or_default().to_string(),
+        size: ByteSize(file.size),
+        content_type: file.content_type.clone(),
+    }
+}
+
+fn file_extension(path: &str) -> Option<&str> {
+    let name = path.rsplit('/').next()?;
+    match name.rsplit_once('.') {
+        Some((stem, ext)) if !stem.is_empty() => Some(ext),
+        _ => None,
+    }
+}
+
+/// Parse the part of a size operator after "size", e.g. ">10mb"
+fn parse_size_filter(filter: &str) -> Option<SizeFilter> {
+    let (comparison, amount) = [
+        (">=", SizeComparison::GreaterOrEqual),
+        ("<=", SizeComparison::LessOrEqual),
+        (">", SizeComparison::Greater),
+        ("<", SizeComparison::Less),
+        ("=", SizeComparison::Equal),
+        (":", SizeComparison::Equal),
+    ]
+    .into_iter()
+    .find_map(|(op, comparison)| filter.strip_prefix(op).map(|rest| (comparison, rest)))?;
+
+    let digits_end = amount
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(amount.len());
+    let (number, unit) = amount.split_at(digits_end);
+    let number: f64 = number.parse().ok()?;
+    let multiplier: u64 = match unit {
+        "" | "b" => 1,
+        "kb" | "k" => 1 << 10,
+        "mb" | "m" => 1 << 20,
+        "gb" | "g" => 1 << 30,
+        "tb" | "t" => 1 << 40,
+        _ => return None,
+    };
+
+    Some(SizeFilter {
+        comparison,
+        bytes: (number * multiplier as f64) as u64,
+    })
+}
+
+fn invalid_operator(word: &str, example: &str) -> VaultError {
+    VaultError::InvalidOperation(format!(
+        "Couldn't read search operator '{}'; expected something like '{}'",
+        word, example
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn file(path: &str, size: u64) -> IndexedFile {
+        IndexedFile {
+            path: path.to_string(),
+            size,
+            content_type: None,
+        }
+    }
+
+    fn archive(vault: &str, id: &str, day: u32, files: Vec<IndexedFile>) -> IndexedArchive {
+        IndexedArchive {
+            vault_id: format!("{vault}-id"),
+            vault_name: vault.to_string(),
+            archive_id: id.to_string(),
+            archive_name: format!("{vault}.age"),
+            created_at: Utc.with_ymd_and_hms(2026, 3, day, 9, 0, 0).unwrap(),
+            comment: None,
+            files,
+        }
+    }
+
+    fn paths(results: &FileSearchResults) -> Vec<&str> {
+        results
+            .vaults
+            .iter()
+            .flat_map(|vault| &vault.archives)
+            .flat_map(|archive| &archive.files)
+            .map(|file| file.path.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_operators_and_phrases() {
+        let query = FileQuery::parse(r#"ext:.PDF  size>10mb "Tax Returns" 2019"#).unwrap();
+        assert_eq!(query.terms, vec!["tax returns", "2019"]);
+        assert_eq!(query.extensions, vec!["pdf"]);
+        assert_eq!(
+            query.size_filters,
+            vec![SizeFilter {
+                comparison: SizeComparison::Greater,
+                bytes: 10 * 1024 * 1024,
+            }]
+        );
+
+        let query = FileQuery::parse("size<=1.5kb size=0").unwrap();
+        assert_eq!(query.size_filters[0].bytes, 1536);
+        assert_eq!(query.size_filters[1].comparison, SizeComparison::Equal);
+
+        assert!(FileQuery::parse("ext:").is_err());
+        assert!(FileQuery::parse("size>lots").is_err());
+        assert!(FileQuery::parse("size>10xb").is_err());
+        assert!(FileQuery::parse("   ").unwrap().is_empty());
+        assert_eq!(FileQuery::parse("sizes").unwrap().terms, vec!["sizes"]);
+    }
+
+    #[test]
+    fn test_operators_filter_files() {
+        let archives = vec![archive(
+            "Family",
+            "a1",
+            1,
+            vec![
+                file("taxes/tax-2019.pdf", 20 * 1024 * 1024),
+                file("taxes/tax-2019.xlsx", 30 * 1024 * 1024),
+                file("taxes/tax-2020.pdf", 1024),
+                file("photos/beach.jpg", 5 * 1024 * 1024),
+            ],
+        )];
+        let search = |q: &str| search_indexed_files(&archives, &FileQuery::parse(q).unwrap());
+
+        assert_eq!(
+            paths(&search("tax-2019")),
+            vec!["taxes/tax-2019.pdf", "taxes/tax-2019.xlsx"]
+        );
+        assert_eq!(
+            paths(&search("ext:pdf")),
+            vec!["taxes/tax-2019.pdf", "taxes/tax-2020.pdf"]
+        );
+        assert_eq!(
+            paths(&search("ext:pdf size>10mb")),
+            vec!["taxes/tax-2019.pdf"]
+        );
+        assert_eq!(paths(&search("taxes size<2kb")), vec!["taxes/tax-2020.pdf"]);
+        assert_eq!(
+            paths(&search(r#""photos/beach""#)),
+            vec!["photos/beach.jpg"]
+        );
+        assert!(paths(&search(r#""beach photos""#)).is_empty());
+        assert!(search("").vaults.is_empty());
+    }
+
+    #[test]
+    fn test_normalization_matches_decomposed_names() {
+        let decomposed = "cv/Re\u{301}sume\u{301}.PDF";
+        let archives = vec![archive("Work", "a1", 1, vec![file(decomposed, 10)])];
+
+        let results = search_indexed_files(&archives, &FileQuery::parse("résumé").unwrap());
+        assert_eq!(paths(&results), vec![decomposed]);
+        assert_eq!(
+            results.vaults[0].archives[0].files[0].file_name,
+            "Re\u{301}sume\u{301}.PDF"
+        );
+
+        let by_ext = search_indexed_files(&archives, &FileQuery::parse("ext:pdf").unwrap());
+        assert_eq!(by_ext.total_matches, 1);
+    }
+
+    #[test]
+    fn test_comment_and_content_type_match_and_grouping() {
+        let mut older = archive("Family", "a1", 1, vec![file("deed.pdf", 10)]);
+        older.comment = Some("Grandma's house".to_string());
+        let newer = archive("Family", "a2", 2, vec![file("house/deed.pdf", 10)]);
+        let mut typed = file("scan-001", 10);
+        typed.content_type = Some("application/pdf".to_string());
+        let other = archive("Archive", "b1", 3, vec![typed]);
+        let archives = vec![older, newer, other];
+
+        let results = search_indexed_files(&archives, &FileQuery::parse("house").unwrap());
+        let archive_ids: Vec<_> = results.vaults[0]
+            .archives
+            .iter()
+            .map(|a| a.archive_id.as_str())
+            .collect();
+        assert_eq!(results.vaults.len(), 1);
+        assert_eq!(archive_ids, vec!["a2", "a1"]);
+
+        let results = search_indexed_files(&archives, &FileQuery::parse("pdf").unwrap());
+        let vaults: Vec<_> = results
+            .vaults
+            .iter()
+            .map(|v| v.vault_name.as_str())
+            .collect();
+        assert_eq!(vaults, vec!["Archive", "Family"]);
+        assert_eq!(results.total_matches, 3);
+    }
+
+    #[test]
+    fn test_results_are_capped() {
+        let files = (0..MAX_FILE_MATCHES + 5)
+            .map(|i| file(&format!("doc-{i}.txt"), 1))
+            .collect();
+        let archives = vec![archive("Bulk", "a1", 1, files)];
+
+        let results = search_indexed_files(&archives, &FileQuery::parse("doc").unwrap());
+        assert!(results.truncated);
+        assert_eq!(results.total_matches, MAX_FILE_MATCHES + 5);
+        assert_eq!(paths(&results).len(), MAX_FILE_MATCHES);
+    }
+
+    #[test]
+    fn test_scope() {
+        assert!(FileSearchScope::AllVaults.includes("any"));
+        let scope = FileSearchScope::Selected(vec!["v1".to_string()]);
+        assert!(scope.includes("v1"));
+        assert!(!scope.includes("v2"));
+    }
+}
@@ -0,0 +1,333 @@
+//! Post-operation hook models
+//!
+//! A hook runs a user-chosen program after a vault operation, e.g. to copy a
+//! new archive to a NAS or ping monitoring. Hooks are opt-in and configured
+//! per vault on this device. Arguments are passed to the program directly as
+//! argv, never through a shell, so a placeholder value can't inject commands
+//! however it is quoted.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// Longest a hook may run before it is killed
+pub const MAX_HOOK_TIMEOUT_SECS: u32 = 600;
+
+/// Timeout when a definition doesn't set one
+pub const DEFAULT_HOOK_TIMEOUT_SECS: u32 = 30;
+
+/// Longest a command's response may wait for its hooks
+pub const MAX_HOOK_GRACE_SECS: u32 = 10;
+
+/// Most hooks per vault
+pub const MAX_HOOKS_PER_VAULT: usize = 16;
+
+/// Most arguments per hook
+pub const MAX_HOOK_ARGS: usize = 32;
+
+/// Operation outcome a hook runs after
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, specta::Type)]
+#[serde(rename_all = "snake_case")]
+pub enum HookEvent {
+    EncryptionCompleted,
+    DecryptionCompleted,
+    /// A restore's files didn't match the manifest
+    VerificationFailed,
+}
+
+impl HookEvent {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::EncryptionCompleted => "encryption_completed",
+            Self::DecryptionCompleted => "decryption_completed",
+            Self::VerificationFailed => "verification_failed",
+        }
+    }
+}
+
+/// One program to run after an operation
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+pub struct HookDefinition {
+    /// Unique within the vault
+    pub id: String,
+    pub event: HookEvent,
+    /// Absolute path to an executable file
+    pub executable: String,
+    /// Argument templates; `{archive_path}`, `{vault_name}`, `{vault_id}`,
+    /// `{status}` and `{event}` are replaced, `{{` and `}}` are literal braces
+    #[serde(default)]
+    pub args: Vec<String>,
+    #[serde(default = "default_timeout_secs")]
+    pub timeout_secs: u32,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+fn default_timeout_secs() -> u32 {
+    DEFAULT_HOOK_TIMEOUT_SECS
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+/// A vault's hooks
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+pub struct VaultHooks {
+    #[serde(default)]
+    pub hooks: Vec<HookDefinition>,
+    /// Seconds a command's response may wait for its hooks to finish; 0 (the
+    /// default) runs them fully detached
+    #[serde(default)]
+    pub response_grace_secs: u32,
+}
+
+impl VaultHooks {
+    pub fn is_empty(&self) -> bool {
+        self.hooks.is_empty() && self.response_grace_secs == 0
+    }
+
+    /// Enabled hooks for `event`
+    pub fn for_event(&self, event: HookEvent) -> impl Iterator<Item = &HookDefinition> {
+        self.hooks
+            .iter()
+            .filter(move |hook| hook.enabled && hook.event == event)
+    }
+}
+
+/// Values substituted into a hook's argument templates
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HookContext {
+    pub event: HookEvent,
+    pub vault_id: String,
+    pub vault_name: String,
+    pub archive_path: String,
+    /// e.g. "success", "unverified", "mismatch", "test"
+    pub status: String,
+}
+
+impl HookContext {
+    fn values(&self) -> BTreeMap<&'static str, &str> {
+        BTreeMap::from([
+            ("archive_path", self.archive_path.as_str()),
+            ("vault_name", self.vault_name.as_str()),
+            ("vault_id", self.vault_id.as_str()),
+            ("status", self.status.as_str()),
+            ("event", self.event.as_str()),
+        ])
+    }
+}
+
+/// Fill in one argument template
+///
+/// Each placeholder becomes exactly its value, inside the same single argv
+/// entry; nothing is split or re-parsed afterwards.
+pub fn substitute_placeholders(template: &str, context: &HookContext) -> Result<String, String> {
+    let values = context.values();
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(pos) = rest.find(['{', '}']) {
+        out.push_str(&rest[..pos]);
+        let tail = &rest[pos..];
+        if let Some(after) = tail.strip_prefix("{{") {
+            out.push('{');
+            rest = after;
+        } else if let Some(after) = tail.strip_prefix("}}") {
+            out.push('}');
+            rest = after;
+        } else if let Some(body) = tail.strip_prefix('{') {
+            let end = body
+                .find('}')
+                .ok_or_else(|| format!("Unclosed placeholder in '{}'", template))?;
+            let name = &body[..end];
+            let value = values
+                .get(name)
+                .ok_or_else(|| format!("Unknown placeholder '{{{}}}'", name))?;
+            out.push_str(value);
+            rest = &body[end + 1..];
+        } else {
+            return Err(format!("Unmatched '}}' in '{}'", template));
+        }
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+/// Check a vault's hooks before they are saved
+pub fn validate_hooks(hooks: &VaultHooks) -> Result<(), String> {
+    if hooks.hooks.len() > MAX_HOOKS_PER_VAULT {
+        return Err(format!(
+            "A vault can have at most {} hooks",
+            MAX_HOOKS_PER_VAULT
+        ));
+    }
+    if hooks.response_grace_secs > MAX_HOOK_GRACE_SECS {
+        return Err(format!(
+            "Response grace period must be at most {} seconds",
+            MAX_HOOK_GRACE_SECS
+        ));
+    }
+    for (i, hook) in hooks.hooks.iter().enumerate() {
+        if hooks.hooks[..i].iter().any(|other| other.id == hook.id) {
+            return Err(format!("Hook ID '{}' is used twice", hook.id));
+        }
+    }
+    hooks.hooks.iter().try_for_each(validate_hook)
+}
+
+/// Check one hook definition: ID, executable, arguments and timeout
+pub fn validate_hook(hook: &HookDefinition) -> Result<(), String> {
+    if hook.id.trim().is_empty() || hook.id.len() > 64 {
+        return Err("Hook ID must be 1-64 characters".to_string());
+    }
+    if hook.timeout_secs == 0 || hook.timeout_secs > MAX_HOOK_TIMEOUT_SECS {
+        return Err(format!(
+            "Hook '{}': timeout must be between 1 and {} seconds",
+            hook.id, MAX_HOOK_TIMEOUT_SECS
+        ));
+    }
+    if hook.args.len() > MAX_HOOK_ARGS {
+        return Err(format!(
+            "Hook '{}': at most {} arguments are allowed",
+            hook.id, MAX_HOOK_ARGS
+        ));
+    }
+
+    let dummy = HookContext {
+        event: hook.event,
+        vault_id: String::new(),
+        vault_name: String::new(),
+        archive_path: String::new(),
+        status: String::new(),
+    };
+    for arg in &hook.args {
+        if arg.contains('\0') {
+            return Err(format!("Hook '{}': arguments can't contain NUL", hook.id));
+        }
+        substitute_placeholders(arg, &dummy).map_err(|e| format!("Hook '{}': {}", hook.id, e))?;
+    }
+
+    validate_executable(Path::new(&hook.executable))
+        .map_err(|e| format!("Hook '{}': {}", hook.id, e))
+}
+
+fn validate_executable(path: &Path) -> Result<(), String> {
+    if !path.is_absolute() {
+        return Err(format!("'{}' is not an absolute path", path.display()));
+    }
+    let metadata = std::fs::metadata(path)
+        .map_err(|e| format!("'{}' can't be read: {}", path.display(), e))?;
+    if !metadata.is_file() {
+        return Err(format!("'{}' is not a file", path.display()));
+    }
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        if metadata.permissions().mode() & 0o111 == 0 {
+            return Err(format!("'{}' is not executable", path.display()));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn context() -> HookContext {
+        HookContext {
+            event: HookEvent::EncryptionCompleted,
+            vault_id: "vault-001".to_string(),
+            vault_name: "Family Docs".to_string(),
+            archive_path: "/vaults/Family-Docs.age".to_string(),
+            status: "success".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_substitutes_each_placeholder() {
+        let out = substitute_placeholders(
+            "--src={archive_path} --name={vault_name} {status}/{event}/{vault_id}",
+            &context(),
+        )
+        .unwrap();
+        assert_eq!(
+            out,
+            "--src=/vaults/Family-Docs.age --name=Family Docs \
+             success/encryption_completed/vault-001"
+        );
+    }
+
+    #[test]
+    fn test_escaped_braces_and_bad_templates() {
+        assert_eq!(
+            substitute_placeholders("{{status}} is {status}", &context()).unwrap(),
+            "{status} is success"
+        );
+        assert!(substitute_placeholders("{home}", &context()).is_err());
+        assert!(substitute_placeholders("{status", &context()).is_err());
+        assert!(substitute_placeholders("status}", &context()).is_err());
+    }
+
+    #[test]
+    fn test_values_are_not_reparsed() {
+        let mut context = context();
+        context.vault_name = "{archive_path}; rm -rf ~".to_string();
+        assert_eq!(
+            substitute_placeholders("{vault_name}", &context).unwrap(),
+            "{archive_path}; rm -rf ~"
+        );
+    }
+
+    #[test]
+    fn test_validation_rejects_relative_and_missing_executables() {
+        let hook = HookDefinition {
+            id: "nas".to_string(),
+            event: HookEvent::EncryptionCompleted,
+            executable: "scripts/copy.sh".to_string(),
+            args: vec![],
+            timeout_secs: 30,
+            enabled: true,
+        };
+        assert!(validate_hook(&hook).unwrap_err().contains("absolute"));
+
+        let missing = HookDefinition {
+            executable: std::env::temp_dir()
+                .join("barqly-no-such-hook")
+                .display()
+                .to_string(),
+            ..hook.clone()
+        };
+        assert!(validate_hook(&missing).is_err());
+
+        let duplicate = VaultHooks {
+            hooks: vec![hook.clone(), hook],
+            response_grace_secs: 0,
+        };
+        assert!(validate_hooks(&duplicate).unwrap_err().contains("twice"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_validation_requires_executable_bit() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let script = dir.path().join("copy.sh");
+        std::fs::write(&script, "#!/bin/sh\n").unwrap();
+        std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o644)).unwrap();
+
+        let hook = HookDefinition {
+            id: "nas".to_string(),
+            event: HookEvent::EncryptionCompleted,
+            executable: script.display().to_string(),
+            args: vec!["{archive_path}".to_string()],
+            timeout_secs: 30,
+            enabled: true,
+        };
+        assert!(validate_hook(&hook).unwrap_err().contains("not executable"));
+
+        std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755)).unwrap();
+        assert!(validate_hook(&hook).is_ok());
+    }
+}
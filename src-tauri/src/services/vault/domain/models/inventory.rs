@@ -0,0 +1,267 @@
+//! Vault inventory models
+//!
+//! An inventory lists what a vault holds (paths, sizes, item links, and
+//! optionally hashes) so it can be handed to someone who must never receive
+//! the data itself. It's built from the manifest alone; nothing is decrypted.
+//!
+//! CSV output follows RFC 4180 (CRLF rows, quoted fields with doubled quotes)
+//! and starts with a UTF-8 BOM so Excel detects the encoding. Raw columns
+//! (`size_bytes`, `archived_at`) stay machine-readable; each has a display
+//! column beside it formatted in the user's locale.
+
+use crate::services::shared::infrastructure::formatting::{Locale, format_bytes, format_date};
+use crate::services::vault::domain::models::{VaultItem, VaultItemCategory};
+use crate::types::ByteSize;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Schema identifier written into JSON inventories
+pub const INVENTORY_SCHEMA: &str = "barqly.vault.inventory/1";
+
+/// UTF-8 byte order mark, for spreadsheet apps that otherwise guess the encoding
+pub const UTF8_BOM: &str = "\u{feff}";
+
+/// Output format of an inventory export
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+#[serde(rename_all = "snake_case")]
+pub enum InventoryFormat {
+    Csv,
+    Json,
+}
+
+/// An item a file is linked to
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InventoryItemLink {
+    pub category: VaultItemCategory,
+    pub title: String,
+}
+
+/// One file in the inventory
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InventoryFile {
+    pub path: String,
+    pub size: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sha256: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub items: Vec<InventoryItemLink>,
+}
+
+/// A vault's file listing at one archive
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VaultInventory {
+    pub schema: String,
+    pub vault_id: String,
+    pub vault_name: String,
+    pub archive_id: Option<String>,
+    pub encryption_revision: u32,
+    /// When the archive was encrypted; per-file modification times aren't recorded
+    pub archived_at: Option<DateTime<Utc>>,
+    pub generated_at: DateTime<Utc>,
+    pub files: Vec<InventoryFile>,
+}
+
+/// A file as recorded in the manifest
+#[derive(Debug, Clone, Copy)]
+pub struct InventorySource<'a> {
+    pub path: &'a str,
+    pub size: u64,
+    pub sha256: &'a str,
+}
+
+impl VaultInventory {
+    /// Build the file listing, attaching items that link to each path
+    ///
+    /// Files are sorted by path. Hashes are only kept when `include_hashes`.
+    pub fn files_from<'a>(
+        sources: impl IntoIterator<Item = InventorySource<'a>>,
+        items: &[VaultItem],
+        include_hashes: bool,
+    ) -> Vec<InventoryFile> {
+        let mut links: HashMap<&str, Vec<InventoryItemLink>> = HashMap::new();
+        for item in items {
+            for path in &item.linked_paths {
+                links
+                    .entry(path.as_str())
+                    .or_default()
+                    .push(InventoryItemLink {
+                        category: item.category,
+                        title: item.title.clone(),
+                    });
+            }
+        }
+
+        let mut files: Vec<InventoryFile> = sources
+            .into_iter()
+            .map(|source| InventoryFile {
+                path: source.path.to_string(),
+                size: source.size,
+                sha256: include_hashes.then(|| source.sha256.to_string()),
+                items: links.get(source.path).cloned().unwrap_or_default(),
+            })
+            .collect();
+        files.sort_by(|a, b| a.path.cmp(&b.path));
+        files
+    }
+
+    pub fn total_bytes(&self) -> ByteSize {
+        ByteSize(self.files.iter().map(|f| f.size).sum())
+    }
+
+    /// Header row of the CSV form
+    pub fn csv_header(include_hashes: bool) -> Vec<&'static str> {
+        let mut header = vec![
+            "path",
+            "size_bytes",
+            "size",
+            "archived_at",
+            "archived",
+            "items",
+        ];
+        if include_hashes {
+            header.push("sha256");
+        }
+        header
+    }
+
+    /// Render as CSV, BOM first, with display columns in `locale`
+    ///
+    /// Linked items are joined into one cell as `category: title` pairs
+    /// separated by `; `.
+    pub fn to_csv(&self, include_hashes: bool, locale: Locale) -> String {
+        let archived_at = self
+            .archived_at
+            .map(|at| at.to_rfc3339())
+            .unwrap_or_default();
+        let archived = self
+            .archived_at
+            .map(|at| format_date(at, locale))
+            .unwrap_or_default();
+
+        let mut csv = String::from(UTF8_BOM);
+        push_csv_row(&mut csv, Self::csv_header(include_hashes));
+        for file in &self.files {
+            let items = file
+                .items
+                .iter()
+                .map(|link| format!("{}: {}", category_label(link.category), link.title))
+                .collect::<Vec<_>>()
+                .join("; ");
+            let size_bytes = file.size.to_string();
+            let size = format_bytes(file.size, locale);
+            let mut row = vec![
+                file.path.as_str(),
+                size_bytes.as_str(),
+                size.as_str(),
+                archived_at.as_str(),
+                archived.as_str(),
+                items.as_str(),
+            ];
+            if include_hashes {
+                row.push(file.sha256.as_deref().unwrap_or_default());
+            }
+            push_csv_row(&mut csv, row);
+        }
+        csv
+    }
+
+    /// Render as pretty-printed JSON
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string_pretty(self)
+    }
+}
+
+/// Result of writing an inventory to disk
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+pub struct InventoryExportResult {
+    pub vault_id: String,
+    pub archive_id: Option<String>,
+    pub output_path: String,
+    pub format: InventoryFormat,
+    pub file_count: usize,
+    pub total_bytes: ByteSize,
+    pub include_hashes: bool,
+}
+
+fn category_label(category: VaultItemCategory) -> String {
+    serde_json::to_value(category)
+        .ok()
+        .and_then(|v| v.as_str().map(str::to_string))
+        .unwrap_or_default()
+}
+
+fn push_csv_row<'a>(csv: &mut String, fields: impl IntoIterator<Item = &'a str>) {
+    let row: Vec<String> = fields.into_iter().map(escape_csv_field).collect();
+    csv.push_str(&row.join(","));
+    csv.push_str("\r\n");
+}
+
+/// Quote a field when it holds a delimiter, quote, line break, or edge space
+pub fn escape_csv_field(field: &str) -> String {
+    let needs_quotes =
+        field.contains([',', '"', '\r', '\n']) || field.starts_with(' ') || field.ends_with(' ');
+    if needs_quotes {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(category: VaultItemCategory, title: &str, paths: &[&str]) -> VaultItem {
+        VaultItem {
+            id: title.to_string(),
+            category,
+            title: title.to_string(),
+            linked_paths: paths.iter().map(|p| p.to_string()).collect(),
+            linked_archive_ids: Vec::new(),
+            note: None,
+            completed: false,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_escape_csv_field() {
+        assert_eq!(escape_csv_field("plain.txt"), "plain.txt");
+        assert_eq!(escape_csv_field("a,b"), "\"a,b\"");
+        assert_eq!(escape_csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+        assert_eq!(escape_csv_field("two\nlines"), "\"two\nlines\"");
+        assert_eq!(escape_csv_field(" padded"), "\" padded\"");
+    }
+
+    #[test]
+    fn test_files_attach_item_links_and_drop_hashes() {
+        let sources = [
+            InventorySource {
+                path: "will.pdf",
+                size: 10,
+                sha256: "aa",
+            },
+            InventorySource {
+                path: "deed.pdf",
+                size: 20,
+                sha256: "bb",
+            },
+        ];
+        let items = [item(
+            VaultItemCategory::LegalDocument,
+            "Last will",
+            &["will.pdf"],
+        )];
+
+        let files = VaultInventory::files_from(sources, &items, false);
+        assert_eq!(files[0].path, "deed.pdf");
+        assert!(files[0].items.is_empty());
+        assert_eq!(files[1].items[0].title, "Last will");
+        assert!(files.iter().all(|f| f.sha256.is_none()));
+
+        let files = VaultInventory::files_from(sources, &items, true);
+        assert_eq!(files[1].sha256.as_deref(), Some("aa"));
+    }
+}
@@ -0,0 +1,183 @@
+//! Launch diagnostics models
+//!
+//! The interface's first action is a `frontend_ready` handshake carrying the
+//! hash of the bindings it was built against. A launch whose handshake never
+//! arrives within the startup window leaves a diagnostic record behind: the
+//! webview version, the platform, and the page loads and window events seen
+//! while waiting. The record is offered for export on the next start that
+//! does reach the interface.
+
+use crate::types::DurationMs;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+/// Startup window used when the app config doesn't set one
+pub const DEFAULT_FRONTEND_READY_TIMEOUT: DurationMs = DurationMs(30_000);
+
+pub const LAUNCH_DIAGNOSTICS_SCHEMA: &str = "barqly.vault.launch-diagnostics/1";
+
+/// Start of the line in the generated bindings that exports their hash
+pub const BINDINGS_HASH_EXPORT: &str = "export const BINDINGS_HASH";
+
+/// Navigation events kept for a launch record, latest last
+pub const MAX_NAVIGATION_EVENTS: usize = 20;
+
+/// Generated bindings without the lines added after generation: the
+/// `@ts-nocheck` marker and the exported hash itself
+pub fn normalize_bindings(content: &str) -> String {
+    content
+        .lines()
+        .filter(|line| !line.contains("@ts-nocheck") && !line.starts_with(BINDINGS_HASH_EXPORT))
+        .collect::<Vec<_>>()
+        .join("\n")
+        .trim_end()
+        .to_string()
+}
+
+/// SHA-256 of the normalized bindings, as exported in `BINDINGS_HASH`
+pub fn hash_bindings(content: &str) -> String {
+    hex::encode(Sha256::digest(normalize_bindings(content).as_bytes()))
+}
+
+/// How the interface's bindings compare with the backend's
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+#[serde(rename_all = "snake_case")]
+pub enum BindingsDrift {
+    /// Built against the bindings this backend generates
+    InSync,
+    /// Built against other bindings; commands may fail or misread responses
+    Drifted,
+    /// The backend couldn't render its bindings to compare
+    Unknown,
+}
+
+impl BindingsDrift {
+    /// Compare the hash the interface reported with the backend's
+    pub fn compare(reported: &str, expected: Option<&str>) -> Self {
+        match expected {
+            None => Self::Unknown,
+            Some(expected) if reported.trim().eq_ignore_ascii_case(expected.trim()) => Self::InSync,
+            Some(_) => Self::Drifted,
+        }
+    }
+}
+
+/// The interface's `frontend_ready` call
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+pub struct FrontendHandshake {
+    pub ui_version: String,
+    /// `BINDINGS_HASH` the interface was built with
+    pub bindings_hash: String,
+    /// Hash of the bindings this backend generates, when it could be rendered
+    pub expected_bindings_hash: Option<String>,
+    pub drift: BindingsDrift,
+    pub received_at: DateTime<Utc>,
+    /// Time from the backend starting the window to the handshake
+    pub elapsed: DurationMs,
+}
+
+/// What the webview did while the handshake was awaited
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+#[serde(rename_all = "snake_case")]
+pub enum NavigationEventKind {
+    /// A page started loading
+    LoadStarted,
+    /// A page finished loading; a load that never finishes points at the
+    /// webview, a finished one without a handshake at the interface's scripts
+    LoadFinished,
+    /// The window was closed or destroyed
+    WindowClosed,
+}
+
+/// A page load or window event seen before the handshake
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+pub struct NavigationEvent {
+    pub at: DateTime<Utc>,
+    pub window_label: String,
+    pub kind: NavigationEventKind,
+    pub url: Option<String>,
+}
+
+/// How a recorded launch ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+#[serde(rename_all = "snake_case")]
+pub enum LaunchOutcome {
+    /// The interface never called `frontend_ready`
+    NoHandshake,
+    /// The interface called `frontend_ready` after the startup window closed
+    LateHandshake,
+}
+
+impl LaunchOutcome {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::NoHandshake => "no_handshake",
+            Self::LateHandshake => "late_handshake",
+        }
+    }
+}
+
+/// Diagnostic record of a launch whose interface didn't load in time
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+pub struct LaunchDiagnostics {
+    pub schema: String,
+    pub app_version: String,
+    /// e.g. "macos"
+    pub os: String,
+    /// e.g. "aarch64"
+    pub arch: String,
+    /// Version of the system webview, when Tauri could read it
+    pub webview_version: Option<String>,
+    /// Hash of the bindings this backend generates, when it could be rendered
+    pub bindings_hash: Option<String>,
+    pub started_at: DateTime<Utc>,
+    /// Startup window the handshake was awaited for
+    pub timeout: DurationMs,
+    pub recorded_at: DateTime<Utc>,
+    pub outcome: LaunchOutcome,
+    /// The handshake, when it arrived late
+    pub handshake: Option<FrontendHandshake>,
+    /// Latest page loads and window events before the window closed
+    pub navigation_events: Vec<NavigationEvent>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_drift_comparison() {
+        let hash = hash_bindings("export const commands = {};");
+
+        assert_eq!(
+            BindingsDrift::compare(&hash, Some(&hash)),
+            BindingsDrift::InSync
+        );
+        assert_eq!(
+            BindingsDrift::compare(&hash.to_uppercase(), Some(&hash)),
+            BindingsDrift::InSync
+        );
+        assert_eq!(
+            BindingsDrift::compare(
+                &hash,
+                Some(&hash_bindings("export const commands = { a };"))
+            ),
+            BindingsDrift::Drifted
+        );
+        assert_eq!(BindingsDrift::compare(&hash, None), BindingsDrift::Unknown);
+    }
+
+    #[test]
+    fn test_hash_ignores_lines_added_after_generation() {
+        let generated = "// Bindings version: 2\nexport const commands = {};\n";
+        let written = format!(
+            "// @ts-nocheck - Suppress TypeScript warnings\n{}\n\n{} = \"{}\";\n",
+            generated.trim_end(),
+            BINDINGS_HASH_EXPORT,
+            hash_bindings(generated)
+        );
+
+        assert_eq!(hash_bindings(&written), hash_bindings(generated));
+    }
+}
@@ -0,0 +1,250 @@
+//! Bulk vault maintenance models
+//!
+//! A maintenance run is a matrix of tasks × vaults. Every cell is executed
+//! independently and reported on its own, so one broken vault never hides the
+//! results for the rest.
+
+use crate::types::DurationMs;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Which vaults a maintenance run covers
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+#[serde(tag = "kind", content = "vault_ids", rename_all = "snake_case")]
+pub enum MaintenanceScope {
+    AllVaults,
+    Vaults(Vec<String>),
+}
+
+/// A maintenance task run against each vault in scope
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, specta::Type)]
+#[serde(rename_all = "snake_case")]
+pub enum MaintenanceTask {
+    /// Recompute statistics and flag orphaned or incomplete vaults
+    StatisticsRefresh,
+    /// Check that the encrypted archive is present and has a readable age header
+    IntegrityVerification,
+    /// Rewrite the external manifest in the current schema
+    ManifestMigration,
+    /// Merge manifest recipients into the key registry
+    RegistryReconciliation,
+}
+
+impl MaintenanceTask {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::StatisticsRefresh => "Statistics refresh",
+            Self::IntegrityVerification => "Integrity verification",
+            Self::ManifestMigration => "Manifest migration",
+            Self::RegistryReconciliation => "Registry reconciliation",
+        }
+    }
+}
+
+/// Outcome of one task on one vault
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+#[serde(rename_all = "snake_case")]
+pub enum MaintenanceCellStatus {
+    Passed,
+    /// Task ran and found problems that need attention
+    IssuesFound,
+    /// Task couldn't run to completion
+    Failed,
+}
+
+/// Findings from a task that ran to completion
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MaintenanceFindings {
+    /// Problems that need the user's attention
+    pub issues: Vec<String>,
+    /// Changes the task made (e.g. "Added 2 keys to the registry")
+    pub actions: Vec<String>,
+}
+
+/// One cell of the maintenance matrix
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+pub struct MaintenanceCell {
+    pub vault_id: String,
+    pub vault_name: String,
+    pub task: MaintenanceTask,
+    pub status: MaintenanceCellStatus,
+    pub duration_ms: DurationMs,
+    pub issues: Vec<String>,
+    pub actions: Vec<String>,
+    /// Why the task failed (Failed cells only)
+    pub error: Option<String>,
+}
+
+impl MaintenanceCell {
+    /// Build a cell from a task result
+    pub fn from_result(
+        vault_id: &str,
+        vault_name: &str,
+        task: MaintenanceTask,
+        duration_ms: DurationMs,
+        result: Result<MaintenanceFindings, String>,
+    ) -> Self {
+        let (status, findings, error) = match result {
+            Ok(findings) if findings.issues.is_empty() => {
+                (MaintenanceCellStatus::Passed, findings, None)
+            }
+            Ok(findings) => (MaintenanceCellStatus::IssuesFound, findings, None),
+            Err(e) => (
+                MaintenanceCellStatus::Failed,
+                MaintenanceFindings::default(),
+                Some(e),
+            ),
+        };
+
+        Self {
+            vault_id: vault_id.to_string(),
+            vault_name: vault_name.to_string(),
+            task,
+            status,
+            duration_ms,
+            issues: findings.issues,
+            actions: findings.actions,
+            error,
+        }
+    }
+}
+
+/// Combined report for a maintenance run
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+pub struct MaintenanceReport {
+    pub report_id: String,
+    /// Progress stream the run reported under
+    pub operation_id: String,
+    pub started_at: DateTime<Utc>,
+    pub finished_at: DateTime<Utc>,
+    pub tasks: Vec<MaintenanceTask>,
+    pub vault_ids: Vec<String>,
+    /// Ordered by vault, then by task as requested
+    pub cells: Vec<MaintenanceCell>,
+    pub passed: usize,
+    pub issues_found: usize,
+    pub failed: usize,
+}
+
+impl MaintenanceReport {
+    pub fn new(
+        operation_id: &str,
+        started_at: DateTime<Utc>,
+        tasks: Vec<MaintenanceTask>,
+        vault_ids: Vec<String>,
+        mut cells: Vec<MaintenanceCell>,
+    ) -> Self {
+        let vault_order = |id: &str| vault_ids.iter().position(|v| v == id);
+        let task_order = |task: &MaintenanceTask| tasks.iter().position(|t| t == task);
+        cells.sort_by_key(|cell| (vault_order(&cell.vault_id), task_order(&cell.task)));
+
+        let count = |status| cells.iter().filter(|c| c.status == status).count();
+        let passed = count(MaintenanceCellStatus::Passed);
+        let issues_found = count(MaintenanceCellStatus::IssuesFound);
+        let failed = count(MaintenanceCellStatus::Failed);
+
+        Self {
+            report_id: uuid::Uuid::new_v4().to_string(),
+            operation_id: operation_id.to_string(),
+            started_at,
+            finished_at: Utc::now(),
+            tasks,
+            vault_ids,
+            cells,
+            passed,
+            issues_found,
+            failed,
+        }
+    }
+
+    /// Cell for a vault and task, if it was part of the run
+    pub fn cell(&self, vault_id: &str, task: MaintenanceTask) -> Option<&MaintenanceCell> {
+        self.cells
+            .iter()
+            .find(|c| c.vault_id == vault_id && c.task == task)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_report_orders_cells_and_counts_statuses() {
+        let tasks = vec![
+            MaintenanceTask::IntegrityVerification,
+            MaintenanceTask::StatisticsRefresh,
+        ];
+        let vault_ids = vec!["b".to_string(), "a".to_string()];
+        let findings = |issues: &[&str]| MaintenanceFindings {
+            issues: issues.iter().map(|s| s.to_string()).collect(),
+            actions: Vec::new(),
+        };
+        let cells = vec![
+            MaintenanceCell::from_result(
+                "a",
+                "A",
+                MaintenanceTask::StatisticsRefresh,
+                DurationMs(1),
+                Ok(findings(&[])),
+            ),
+            MaintenanceCell::from_result(
+                "b",
+                "B",
+                MaintenanceTask::StatisticsRefresh,
+                DurationMs(1),
+                Err("boom".to_string()),
+            ),
+            MaintenanceCell::from_result(
+                "a",
+                "A",
+                MaintenanceTask::IntegrityVerification,
+                DurationMs(1),
+                Ok(findings(&["bad header"])),
+            ),
+            MaintenanceCell::from_result(
+                "b",
+                "B",
+                MaintenanceTask::IntegrityVerification,
+                DurationMs(1),
+                Ok(findings(&[])),
+            ),
+        ];
+
+        let report = MaintenanceReport::new("op", Utc::now(), tasks, vault_ids, cells);
+        let order: Vec<_> = report
+            .cells
+            .iter()
+            .map(|c| (c.vault_id.as_str(), c.task))
+            .collect();
+        assert_eq!(
+            order,
+            vec![
+                ("b", MaintenanceTask::IntegrityVerification),
+                ("b", MaintenanceTask::StatisticsRefresh),
+                ("a", MaintenanceTask::IntegrityVerification),
+                ("a", MaintenanceTask::StatisticsRefresh),
+            ]
+        );
+        assert_eq!(
+            (report.passed, report.issues_found, report.failed),
+            (2, 1, 1)
+        );
+
+        let failed = report
+            .cell("b", MaintenanceTask::StatisticsRefresh)
+            .unwrap();
+        assert_eq!(failed.error.as_deref(), Some("boom"));
+        assert!(failed.issues.is_empty());
+    }
+
+    #[test]
+    fn test_scope_serialization() {
+        let json = serde_json::to_string(&MaintenanceScope::AllVaults).unwrap();
+        assert_eq!(json, r#"{"kind":"all_vaults"}"#);
+
+        let scope: MaintenanceScope =
+            serde_json::from_str(r#"{"kind":"vaults","vault_ids":["v1"]}"#).unwrap();
+        assert_eq!(scope, MaintenanceScope::Vaults(vec!["v1".to_string()]));
+    }
+}
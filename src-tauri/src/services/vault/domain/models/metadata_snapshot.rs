@@ -0,0 +1,75 @@
+//! Metadata snapshot models
+//!
+//! Restore points for the key registry, vault manifests and archive index,
+//! and the changes between a restore point and the current state, described
+//! in terms of keys, vaults and archives rather than files.
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+/// Text the user must type to restore a metadata snapshot
+pub const RESTORE_SNAPSHOT_CONFIRMATION: &str = "RESTORE";
+
+/// A restore point as listed to the user
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, specta::Type)]
+pub struct MetadataSnapshotSummary {
+    pub snapshot_id: String,
+    pub created_at: DateTime<Utc>,
+    /// Operation that was about to run, e.g. "attach_key_to_vault"
+    pub operation: String,
+    pub manifest_count: usize,
+    pub includes_registry: bool,
+    pub includes_archive_index: bool,
+}
+
+/// What kind of change a `MetadataChange` describes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, specta::Type)]
+#[serde(rename_all = "snake_case")]
+pub enum MetadataChangeKind {
+    KeyAdded,
+    KeyRemoved,
+    KeyRenamed,
+    VaultAdded,
+    VaultRemoved,
+    VaultRenamed,
+    KeyAttached,
+    KeyDetached,
+    ArchivesIndexed,
+    ArchivesRemoved,
+    ArchivesUpdated,
+}
+
+/// One change, worded for the user
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, specta::Type)]
+pub struct MetadataChange {
+    pub kind: MetadataChangeKind,
+    pub description: String,
+}
+
+impl MetadataChange {
+    pub fn new(kind: MetadataChangeKind, description: impl Into<String>) -> Self {
+        Self {
+            kind,
+            description: description.into(),
+        }
+    }
+}
+
+/// Changes made since a restore point
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, specta::Type)]
+pub struct MetadataSnapshotDiff {
+    pub snapshot: MetadataSnapshotSummary,
+    /// Empty when nothing changed
+    pub changes: Vec<MetadataChange>,
+}
+
+/// Result of restoring a restore point
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, specta::Type)]
+pub struct MetadataRestoreResult {
+    pub snapshot_id: String,
+    pub restored_files: usize,
+    /// Restore point of the state just replaced, so the restore can be undone
+    pub undo_snapshot_id: Option<String>,
+    /// Changes that were reverted
+    pub reverted: Vec<MetadataChange>,
+}
@@ -1,5 +1,61 @@
+pub mod app_compatibility;
+pub mod archive;
+pub mod archive_patch;
+pub mod clipboard;
+pub mod compatibility_changes;
+pub mod dead_mans_switch;
+pub mod directory_comparison;
+pub mod eject;
+pub mod file_search;
+pub mod hook;
+pub mod inventory;
+pub mod launch_diagnostics;
+pub mod maintenance;
+pub mod metadata_snapshot;
+pub mod name_validator;
+pub mod notification;
+pub mod onboarding;
+pub mod output_naming;
+pub mod quarantine;
+pub mod retention;
+pub mod shared_store;
+pub mod shutdown;
+pub mod statistics_history;
+pub mod storage_usage;
 pub mod vault;
+pub mod vault_generation;
+pub mod vault_item;
+pub mod vault_risk;
 pub mod vault_rules;
+pub mod vault_template;
 
+pub use app_compatibility::*;
+pub use archive::*;
+pub use archive_patch::*;
+pub use clipboard::*;
+pub use compatibility_changes::*;
+pub use dead_mans_switch::*;
+pub use directory_comparison::*;
+pub use eject::*;
+pub use file_search::*;
+pub use hook::*;
+pub use inventory::*;
+pub use launch_diagnostics::*;
+pub use maintenance::*;
+pub use metadata_snapshot::*;
+pub use name_validator::*;
+pub use notification::*;
+pub use onboarding::*;
+pub use output_naming::*;
+pub use quarantine::*;
+pub use retention::*;
+pub use shared_store::*;
+pub use shutdown::*;
+pub use statistics_history::*;
+pub use storage_usage::*;
 pub use vault::*;
+pub use vault_generation::*;
+pub use vault_item::*;
+pub use vault_risk::*;
 pub use vault_rules::*;
+pub use vault_template::*;
@@ -0,0 +1,366 @@
+//! Validation for user-chosen vault names and key labels
+//!
+//! Vault names become directory and archive file names, and both kinds of
+//! name are what the user picks from in lists. New names are normalized and
+//! checked here before they are written; names already on disk are never
+//! re-validated on read, so older vaults keep working and bootstrap only
+//! warns about them.
+
+use super::super::errors::{VaultError, VaultResult};
+use unicode_normalization::UnicodeNormalization;
+
+/// What a name is for, which decides its limits
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NameKind {
+    VaultName,
+    KeyLabel,
+}
+
+impl NameKind {
+    fn noun(self) -> &'static str {
+        match self {
+            Self::VaultName => "Vault name",
+            Self::KeyLabel => "Key label",
+        }
+    }
+
+    fn max_chars(self) -> usize {
+        match self {
+            Self::VaultName => NameValidator::MAX_VAULT_NAME_CHARS,
+            Self::KeyLabel => NameValidator::MAX_KEY_LABEL_CHARS,
+        }
+    }
+
+    /// Vault names are file names on every platform; labels only need to
+    /// stay out of paths
+    fn forbidden_chars(self) -> &'static [char] {
+        match self {
+            Self::VaultName => &['/', '\\', ':', '*', '?', '"', '<', '>', '|'],
+            Self::KeyLabel => &['/', '\\'],
+        }
+    }
+}
+
+/// Device names Windows reserves in every directory, with or without an extension
+const RESERVED_DEVICE_NAMES: &[&str] = &[
+    "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8",
+    "COM9", "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+/// Validates and normalizes names before they are stored
+pub struct NameValidator;
+
+impl NameValidator {
+    pub const MAX_VAULT_NAME_CHARS: usize = 100;
+    pub const MAX_KEY_LABEL_CHARS: usize = 128;
+
+    /// Normalize a new name and check it, returning the name to store
+    ///
+    /// Zero-width characters are dropped and leading/trailing whitespace and
+    /// dots are trimmed (Windows drops trailing dots, leading dots hide
+    /// files on Unix). Control characters, bidirectional overrides, path
+    /// separators and reserved device names are rejected.
+    pub fn normalize(kind: NameKind, name: &str) -> VaultResult<String> {
+        let noun = kind.noun();
+
+        if let Some(c) = name.chars().find(|&c| c.is_control() || is_bidi_control(c)) {
+            return Err(VaultError::InvalidName(format!(
+                "{} contains a hidden control character (U+{:04X})",
+                noun, c as u32
+            )));
+        }
+
+        let visible: String = name.chars().filter(|&c| !is_zero_width(c)).collect();
+        let trimmed = visible.trim_matches(|c: char| c.is_whitespace() || c == '.');
+
+        if trimmed.is_empty() {
+            return Err(VaultError::InvalidName(format!("{} cannot be empty", noun)));
+        }
+
+        if trimmed.chars().count() > kind.max_chars() {
+            return Err(VaultError::InvalidName(format!(
+                "{} must be at most {} characters",
+                noun,
+                kind.max_chars()
+            )));
+        }
+
+        if let Some(c) = trimmed.chars().find(|c| kind.forbidden_chars().contains(c)) {
+            return Err(VaultError::InvalidName(format!(
+                "{} cannot contain '{}'",
+                noun, c
+            )));
+        }
+
+        let stem = trimmed.split('.').next().unwrap_or(trimmed).trim_end();
+        if RESERVED_DEVICE_NAMES
+            .iter()
+            .any(|reserved| stem.eq_ignore_ascii_case(reserved))
+        {
+            return Err(VaultError::InvalidName(format!(
+                "{} '{}' is reserved by Windows",
+                noun, stem
+            )));
+        }
+
+        Ok(trimmed.to_string())
+    }
+
+    /// Reject a name that looks like, but isn't, one already in use
+    ///
+    /// Identical names are left to the caller's duplicate check.
+    pub fn check_confusable<'a>(
+        name: &str,
+        existing: impl IntoIterator<Item = &'a str>,
+    ) -> VaultResult<()> {
+        let target = Self::skeleton(name);
+        match existing
+            .into_iter()
+            .find(|other| *other != name && Self::skeleton(other) == target)
+        {
+            Some(other) => Err(VaultError::NameConfusable {
+                name: name.to_string(),
+                conflicts_with: other.to_string(),
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// Normalize and check a new name against the names already in use
+    pub fn validate_new<'a>(
+        kind: NameKind,
+        name: &str,
+        existing: impl IntoIterator<Item = &'a str>,
+    ) -> VaultResult<String> {
+        let name = Self::normalize(kind, name)?;
+        Self::check_confusable(&name, existing)?;
+        Ok(name)
+    }
+
+    /// Why a stored name would be refused today, if it would be
+    ///
+    /// Used to warn about names written before validation existed.
+    pub fn nonconformity(kind: NameKind, name: &str) -> Option<String> {
+        match Self::normalize(kind, name) {
+            Ok(normalized) if normalized == name => None,
+            Ok(normalized) => Some(format!("would be stored as '{}'", normalized)),
+            Err(e) => Some(e.to_string()),
+        }
+    }
+
+    /// Comparison form: compatibility-normalized, case-folded, invisible
+    /// characters removed and common cross-script lookalikes mapped to Latin
+    pub fn skeleton(name: &str) -> String {
+        name.nfkc()
+            .filter(|&c| !is_zero_width(c) && !is_bidi_control(c))
+            .map(latin_lookalike)
+            .flat_map(char::to_lowercase)
+            .collect::<String>()
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+fn is_zero_width(c: char) -> bool {
+    matches!(
+        c,
+        '\u{00AD}' | '\u{180E}' | '\u{200B}'..='\u{200D}' | '\u{2060}'..='\u{2064}' | '\u{FEFF}'
+    )
+}
+
+/// Characters that reorder text and can make a name display as another
+fn is_bidi_control(c: char) -> bool {
+    matches!(
+        c,
+        '\u{061C}' | '\u{200E}' | '\u{200F}' | '\u{202A}'..='\u{202E}' | '\u{2066}'..='\u{2069}'
+    )
+}
+
+/// Cyrillic and Greek letters that render like Latin ones
+fn latin_lookalike(c: char) -> char {
+    match c {
+        'А' | 'Α' => 'A',
+        'В' | 'Β' => 'B',
+        'С' | 'Ϲ' => 'C',
+        'Е' | 'Ε' => 'E',
+        'Н' | 'Η' => 'H',
+        'І' | 'Ι' => 'I',
+        'Ј' => 'J',
+        'К' | 'Κ' => 'K',
+        'М' | 'Μ' => 'M',
+        'Ν' => 'N',
+        'О' | 'Ο' => 'O',
+        'Р' | 'Ρ' => 'P',
+        'Ѕ' => 'S',
+        'Т' | 'Τ' => 'T',
+        'Х' | 'Χ' => 'X',
+        'Υ' | 'Ү' => 'Y',
+        'Ζ' => 'Z',
+        'а' | 'α' => 'a',
+        'с' | 'ϲ' => 'c',
+        'ԁ' => 'd',
+        'е' => 'e',
+        'һ' => 'h',
+        'і' | 'ι' => 'i',
+        'ј' => 'j',
+        'κ' => 'k',
+        'о' | 'ο' => 'o',
+        'р' | 'ρ' => 'p',
+        'ԛ' => 'q',
+        'ѕ' => 's',
+        'υ' => 'u',
+        'ν' => 'v',
+        'ԝ' => 'w',
+        'х' | 'χ' => 'x',
+        'у' => 'y',
+        _ => c,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case::plain("Family Vault", "Family Vault")]
+    #[case::punctuation("Sam's Vault (2024) - Main_1", "Sam's Vault (2024) - Main_1")]
+    #[case::unicode("Famille Étienne", "Famille Étienne")]
+    #[case::emoji("Photos 🎉", "Photos 🎉")]
+    #[case::cjk("家族の金庫", "家族の金庫")]
+    #[case::trims_whitespace("  Family  ", "Family")]
+    #[case::trims_trailing_dots("Family...", "Family")]
+    #[case::trims_leading_dots(".hidden", "hidden")]
+    #[case::strips_zero_width("Fam\u{200B}ily\u{FEFF}", "Family")]
+    #[case::strips_soft_hyphen("Fam\u{00AD}ily", "Family")]
+    #[case::inner_dots_kept("v1.2 backup", "v1.2 backup")]
+    #[case::device_name_inside("Console", "Console")]
+    #[case::device_name_prefix("CONFIG", "CONFIG")]
+    fn test_accepts_vault_name(#[case] input: &str, #[case] expected: &str) {
+        assert_eq!(
+            NameValidator::normalize(NameKind::VaultName, input).unwrap(),
+            expected
+        );
+    }
+
+    #[rstest]
+    #[case::empty("")]
+    #[case::blank("   ")]
+    #[case::only_dots("...")]
+    #[case::dot_dot("..")]
+    #[case::only_zero_width("\u{200B}\u{200D}")]
+    #[case::traversal("../../etc")]
+    #[case::slash("My/Vault")]
+    #[case::backslash("My\\Vault")]
+    #[case::colon("C:Vault")]
+    #[case::star("Vault*Name")]
+    #[case::question("Vault?")]
+    #[case::quote("\"Vault\"")]
+    #[case::angle("<Vault>")]
+    #[case::pipe("Vault|Name")]
+    #[case::newline("Family\nVault")]
+    #[case::carriage_return("Family\r")]
+    #[case::tab("Family\tVault")]
+    #[case::nul("Family\0")]
+    #[case::escape("\u{1B}[31mRed")]
+    #[case::rtl_override("Invoice\u{202E}fdp.exe")]
+    #[case::rtl_isolate("\u{2067}Family")]
+    #[case::lrm("Family\u{200E}")]
+    #[case::con("CON")]
+    #[case::nul_lowercase("nul")]
+    #[case::com_with_extension("com1.age")]
+    #[case::lpt_padded("  LPT9  ")]
+    #[case::device_trailing_dot("AUX.")]
+    fn test_rejects_vault_name(#[case] input: &str) {
+        assert!(matches!(
+            NameValidator::normalize(NameKind::VaultName, input),
+            Err(VaultError::InvalidName(_))
+        ));
+    }
+
+    #[rstest]
+    #[case::colon("Work: backup", true)]
+    #[case::email("bob@company.com", true)]
+    #[case::quote("Mom's \"spare\" key", true)]
+    #[case::slash("keys/primary", false)]
+    #[case::backslash("keys\\primary", false)]
+    #[case::newline("Primary\nKey", false)]
+    #[case::rtl_override("Key\u{202E}", false)]
+    #[case::device_name("PRN", false)]
+    fn test_key_label_rules(#[case] input: &str, #[case] accepted: bool) {
+        let result = NameValidator::normalize(NameKind::KeyLabel, input);
+        assert_eq!(result.ok().as_deref(), accepted.then_some(input));
+    }
+
+    #[rstest]
+    #[case::vault_name(NameKind::VaultName, "a", 100)]
+    #[case::vault_name_multibyte(NameKind::VaultName, "é", 100)]
+    #[case::key_label(NameKind::KeyLabel, "k", 128)]
+    fn test_length_limit_counts_characters(
+        #[case] kind: NameKind,
+        #[case] unit: &str,
+        #[case] max: usize,
+    ) {
+        assert!(NameValidator::normalize(kind, &unit.repeat(max)).is_ok());
+        assert!(matches!(
+            NameValidator::normalize(kind, &unit.repeat(max + 1)),
+            Err(VaultError::InvalidName(_))
+        ));
+    }
+
+    #[rstest]
+    #[case::cyrillic_a("Fаmily", "Family")]
+    #[case::cyrillic_capitals("САМ", "Cam")]
+    #[case::greek_omicron("Vοult", "Voult")]
+    #[case::cyrillic_en("НОМЕ", "Home")]
+    #[case::case_only("family vault", "Family Vault")]
+    #[case::fullwidth("Ｆａｍｉｌｙ", "Family")]
+    #[case::zero_width("Fam\u{200D}ily", "Family")]
+    #[case::extra_spaces("Family  Vault", "Family Vault")]
+    #[case::ligature("ﬁnances", "finances")]
+    fn test_detects_confusable(#[case] new_name: &str, #[case] existing: &str) {
+        let result = NameValidator::check_confusable(new_name, [existing]);
+        match result {
+            Err(VaultError::NameConfusable {
+                name,
+                conflicts_with,
+            }) => {
+                assert_eq!(name, new_name);
+                assert_eq!(conflicts_with, existing);
+            }
+            other => panic!("expected NameConfusable, got {:?}", other),
+        }
+    }
+
+    #[rstest]
+    #[case::identical("Family", "Family")]
+    #[case::different("Family", "Business")]
+    #[case::digit_vs_letter("Vault 1", "Vault l")]
+    #[case::accent("Etienne", "Étienne")]
+    #[case::real_cyrillic_word("Семья", "Cemya")]
+    fn test_allows_distinct_names(#[case] new_name: &str, #[case] existing: &str) {
+        assert!(NameValidator::check_confusable(new_name, [existing]).is_ok());
+    }
+
+    #[test]
+    fn test_validate_new_checks_normalized_name() {
+        let existing = ["Family"];
+        let result = NameValidator::validate_new(NameKind::VaultName, " Fаmily. ", existing);
+        assert!(matches!(result, Err(VaultError::NameConfusable { .. })));
+
+        let name = NameValidator::validate_new(NameKind::VaultName, " Business ", existing);
+        assert_eq!(name.unwrap(), "Business");
+    }
+
+    #[test]
+    fn test_nonconformity_of_stored_names() {
+        assert_eq!(
+            NameValidator::nonconformity(NameKind::VaultName, "Family"),
+            None
+        );
+        assert!(NameValidator::nonconformity(NameKind::VaultName, "Family.").is_some());
+        assert!(NameValidator::nonconformity(NameKind::VaultName, "CON").is_some());
+        assert!(NameValidator::nonconformity(NameKind::KeyLabel, "a\u{202E}b").is_some());
+    }
+}
@@ -0,0 +1,200 @@
+//! Vault notification models
+//!
+//! Per-vault notification preferences and the digest entries produced by
+//! evaluating them. Messages are returned as a key plus parameters so the UI
+//! can localize them.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Kind of condition a notification reports
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize, specta::Type,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationCategory {
+    /// Last encryption is older than the stale-backup threshold
+    StaleBackup,
+    /// Keys were attached after the last encryption and are not yet in the archive
+    PendingChanges,
+    /// The vault has not been decrypted (verified) for a while
+    VerificationReminder,
+    /// The retention policy marks archives eligible for pruning
+    PruneEligible,
+    /// The dead man's switch deadline is approaching
+    CheckInDue,
+    /// The dead man's switch interval passed without a check-in
+    CheckInLapsed,
+    /// A reserved YubiKey slot is still waiting for its hardware
+    PendingHardwareKey,
+    /// A forced quit cut an operation on the vault short
+    InterruptedOperation,
+    /// The previous launch ended before the interface loaded; app-wide
+    LaunchFailed,
+}
+
+impl NotificationCategory {
+    pub const ALL: [NotificationCategory; 9] = [
+        NotificationCategory::StaleBackup,
+        NotificationCategory::PendingChanges,
+        NotificationCategory::VerificationReminder,
+        NotificationCategory::PruneEligible,
+        NotificationCategory::CheckInDue,
+        NotificationCategory::CheckInLapsed,
+        NotificationCategory::PendingHardwareKey,
+        NotificationCategory::InterruptedOperation,
+        NotificationCategory::LaunchFailed,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::StaleBackup => "stale_backup",
+            Self::PendingChanges => "pending_changes",
+            Self::VerificationReminder => "verification_reminder",
+            Self::PruneEligible => "prune_eligible",
+            Self::CheckInDue => "check_in_due",
+            Self::CheckInLapsed => "check_in_lapsed",
+            Self::PendingHardwareKey => "pending_hardware_key",
+            Self::InterruptedOperation => "interrupted_operation",
+            Self::LaunchFailed => "launch_failed",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|c| c.as_str() == value)
+    }
+
+    /// Localization key for the notification message
+    pub fn message_key(&self) -> String {
+        format!("notifications.{}", self.as_str())
+    }
+}
+
+/// How urgent a notification is (ordered low to high)
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, specta::Type,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum NotificationSeverity {
+    Info,
+    Warning,
+    Critical,
+}
+
+/// Which notifications a vault raises, and when
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+#[serde(default)]
+pub struct NotificationPreferences {
+    pub stale_backup_enabled: bool,
+    /// Days since the last encryption before the backup counts as stale
+    pub stale_backup_days: u32,
+    pub pending_changes_enabled: bool,
+    /// Days a change may stay unencrypted before alerting
+    pub pending_changes_days: u32,
+    pub verification_reminders_enabled: bool,
+    /// Days without a successful decryption before reminding to verify
+    pub verification_reminder_days: u32,
+    pub prune_eligible_enabled: bool,
+    /// Dead man's switch reminders, before and after the deadline
+    pub check_in_reminders_enabled: bool,
+    pub pending_hardware_key_enabled: bool,
+    /// Days a reserved YubiKey slot may wait for its hardware before alerting
+    pub pending_hardware_key_days: u32,
+}
+
+impl Default for NotificationPreferences {
+    fn default() -> Self {
+        Self {
+            stale_backup_enabled: true,
+            stale_backup_days: 30,
+            pending_changes_enabled: true,
+            pending_changes_days: 1,
+            verification_reminders_enabled: true,
+            verification_reminder_days: 90,
+            prune_eligible_enabled: true,
+            check_in_reminders_enabled: true,
+            pending_hardware_key_enabled: true,
+            pending_hardware_key_days: 7,
+        }
+    }
+}
+
+impl NotificationPreferences {
+    pub fn is_enabled(&self, category: NotificationCategory) -> bool {
+        match category {
+            NotificationCategory::StaleBackup => self.stale_backup_enabled,
+            NotificationCategory::PendingChanges => self.pending_changes_enabled,
+            NotificationCategory::VerificationReminder => self.verification_reminders_enabled,
+            NotificationCategory::PruneEligible => self.prune_eligible_enabled,
+            NotificationCategory::CheckInDue | NotificationCategory::CheckInLapsed => {
+                self.check_in_reminders_enabled
+            }
+            NotificationCategory::PendingHardwareKey => self.pending_hardware_key_enabled,
+            // Always shown: the vault may be missing its latest archive
+            NotificationCategory::InterruptedOperation => true,
+            // Not a vault's; shown until exported or dismissed
+            NotificationCategory::LaunchFailed => true,
+        }
+    }
+}
+
+/// A single entry in the notifications digest
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+pub struct VaultNotification {
+    /// Stable identifier (`<category>:<vault_id>`) used for dismissal
+    pub id: String,
+    pub category: NotificationCategory,
+    pub vault_id: String,
+    pub vault_name: String,
+    pub message_key: String,
+    pub params: BTreeMap<String, String>,
+    pub severity: NotificationSeverity,
+    pub first_triggered_at: DateTime<Utc>,
+}
+
+impl VaultNotification {
+    pub fn make_id(category: NotificationCategory, vault_id: &str) -> String {
+        format!("{}:{}", category.as_str(), vault_id)
+    }
+
+    /// Split a notification ID into its category and vault ID
+    pub fn parse_id(id: &str) -> Option<(NotificationCategory, &str)> {
+        let (category, vault_id) = id.split_once(':')?;
+        if vault_id.is_empty() {
+            return None;
+        }
+        Some((NotificationCategory::parse(category)?, vault_id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_notification_id_round_trip() {
+        let id = VaultNotification::make_id(NotificationCategory::PendingChanges, "vault-001");
+        assert_eq!(id, "pending_changes:vault-001");
+        assert_eq!(
+            VaultNotification::parse_id(&id),
+            Some((NotificationCategory::PendingChanges, "vault-001"))
+        );
+    }
+
+    #[test]
+    fn test_invalid_notification_ids() {
+        assert_eq!(VaultNotification::parse_id("stale_backup:"), None);
+        assert_eq!(VaultNotification::parse_id("unknown:vault-001"), None);
+        assert_eq!(VaultNotification::parse_id("no-separator"), None);
+    }
+
+    #[test]
+    fn test_preferences_fill_missing_fields_with_defaults() {
+        let prefs: NotificationPreferences =
+            serde_json::from_str(r#"{"stale_backup_days": 7}"#).unwrap();
+        assert_eq!(prefs.stale_backup_days, 7);
+        assert!(prefs.pending_changes_enabled);
+        assert_eq!(prefs.verification_reminder_days, 90);
+    }
+}
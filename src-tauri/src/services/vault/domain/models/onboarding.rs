@@ -0,0 +1,226 @@
+//! First-run onboarding models
+//!
+//! Milestones are derived from state the app already keeps (vault manifests,
+//! the key registry, the archive index) rather than tracked separately. Only
+//! the time each milestone was first seen complete is cached, so a milestone
+//! stays completed when, say, a key is later removed.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+/// Step of the first-run checklist
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize, specta::Type,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum OnboardingMilestone {
+    /// At least one vault exists
+    VaultCreated,
+    /// Two active keys of different types, e.g. a passphrase and a YubiKey
+    KeysOfTwoTypes,
+    /// A vault has been encrypted
+    FirstEncryption,
+    /// An archive has been decrypted and checked against its manifest
+    VerifiedRestore,
+    /// Recovery shares have been created for a vault
+    RecoveryKitExported,
+}
+
+impl OnboardingMilestone {
+    /// In checklist order
+    pub const ALL: [Self; 5] = [
+        Self::VaultCreated,
+        Self::KeysOfTwoTypes,
+        Self::FirstEncryption,
+        Self::VerifiedRestore,
+        Self::RecoveryKitExported,
+    ];
+}
+
+/// Completion of one milestone
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+pub struct MilestoneStatus {
+    pub milestone: OnboardingMilestone,
+    pub completed: bool,
+    pub completed_at: Option<DateTime<Utc>>,
+}
+
+/// First-run checklist, milestones in order
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+pub struct OnboardingStatus {
+    pub milestones: Vec<MilestoneStatus>,
+    pub all_completed: bool,
+}
+
+/// Backend state milestones are derived from
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OnboardingFacts {
+    /// Creation time of each vault
+    pub vaults_created: Vec<DateTime<Utc>>,
+    /// Type ("passphrase", "yubikey", "recipient") and creation time of each
+    /// active key
+    pub active_keys: Vec<(String, DateTime<Utc>)>,
+    /// Times vaults were encrypted
+    pub encryptions: Vec<DateTime<Utc>>,
+    /// Times keys were used for a verified decryption
+    pub verified_restores: Vec<DateTime<Utc>>,
+    /// Creation time of each vault's recovery shares
+    pub recovery_kits: Vec<DateTime<Utc>>,
+}
+
+impl OnboardingFacts {
+    /// Milestones complete right now, with the earliest time each became so
+    pub fn derive(&self) -> BTreeMap<OnboardingMilestone, DateTime<Utc>> {
+        let candidates = [
+            (
+                OnboardingMilestone::VaultCreated,
+                earliest(&self.vaults_created),
+            ),
+            (
+                OnboardingMilestone::KeysOfTwoTypes,
+                self.second_key_type_added(),
+            ),
+            (
+                OnboardingMilestone::FirstEncryption,
+                earliest(&self.encryptions),
+            ),
+            (
+                OnboardingMilestone::VerifiedRestore,
+                earliest(&self.verified_restores),
+            ),
+            (
+                OnboardingMilestone::RecoveryKitExported,
+                earliest(&self.recovery_kits),
+            ),
+        ];
+
+        candidates
+            .into_iter()
+            .filter_map(|(milestone, at)| Some((milestone, at?)))
+            .collect()
+    }
+
+    /// When a key of a second type was first added alongside the first
+    fn second_key_type_added(&self) -> Option<DateTime<Utc>> {
+        let mut first_of_type: HashMap<&str, DateTime<Utc>> = HashMap::new();
+        for (key_type, created_at) in &self.active_keys {
+            first_of_type
+                .entry(key_type.as_str())
+                .and_modify(|at| *at = (*at).min(*created_at))
+                .or_insert(*created_at);
+        }
+
+        let mut times: Vec<_> = first_of_type.into_values().collect();
+        times.sort();
+        times.get(1).copied()
+    }
+}
+
+fn earliest(times: &[DateTime<Utc>]) -> Option<DateTime<Utc>> {
+    times.iter().min().copied()
+}
+
+impl OnboardingStatus {
+    /// Combine cached completion times with what's derivable now
+    ///
+    /// Cached milestones stay completed even if the state behind them is gone.
+    /// Returns the status and the milestones that just became complete; the
+    /// caller adds those to the cache.
+    pub fn merge(
+        cached: &BTreeMap<OnboardingMilestone, DateTime<Utc>>,
+        derived: &BTreeMap<OnboardingMilestone, DateTime<Utc>>,
+    ) -> (Self, Vec<MilestoneStatus>) {
+        let mut newly_completed = Vec::new();
+        let milestones: Vec<_> = OnboardingMilestone::ALL
+            .into_iter()
+            .map(|milestone| {
+                let completed_at = match (cached.get(&milestone), derived.get(&milestone)) {
+                    (Some(at), _) => Some(*at),
+                    (None, Some(at)) => {
+                        newly_completed.push(MilestoneStatus {
+                            milestone,
+                            completed: true,
+                            completed_at: Some(*at),
+                        });
+                        Some(*at)
+                    }
+                    (None, None) => None,
+                };
+                MilestoneStatus {
+                    milestone,
+                    completed: completed_at.is_some(),
+                    completed_at,
+                }
+            })
+            .collect();
+
+        let all_completed = milestones.iter().all(|m| m.completed);
+        (
+            Self {
+                milestones,
+                all_completed,
+            },
+            newly_completed,
+        )
+    }
+
+    pub fn milestone(&self, milestone: OnboardingMilestone) -> Option<&MilestoneStatus> {
+        self.milestones.iter().find(|m| m.milestone == milestone)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn day(n: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 1, n, 12, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn test_two_keys_of_the_same_type_dont_count() {
+        let facts = OnboardingFacts {
+            active_keys: vec![("passphrase".into(), day(1)), ("passphrase".into(), day(2))],
+            ..Default::default()
+        };
+        assert!(
+            !facts
+                .derive()
+                .contains_key(&OnboardingMilestone::KeysOfTwoTypes)
+        );
+
+        let facts = OnboardingFacts {
+            active_keys: vec![
+                ("passphrase".into(), day(3)),
+                ("yubikey".into(), day(5)),
+                ("passphrase".into(), day(1)),
+            ],
+            ..Default::default()
+        };
+        assert_eq!(
+            facts.derive().get(&OnboardingMilestone::KeysOfTwoTypes),
+            Some(&day(5))
+        );
+    }
+
+    #[test]
+    fn test_merge_keeps_cached_time() {
+        let cached = BTreeMap::from([(OnboardingMilestone::VaultCreated, day(1))]);
+        let derived = BTreeMap::from([
+            (OnboardingMilestone::VaultCreated, day(4)),
+            (OnboardingMilestone::FirstEncryption, day(6)),
+        ]);
+
+        let (status, newly_completed) = OnboardingStatus::merge(&cached, &derived);
+        let vault = status.milestone(OnboardingMilestone::VaultCreated).unwrap();
+        assert_eq!(vault.completed_at, Some(day(1)));
+        assert_eq!(newly_completed.len(), 1);
+        assert_eq!(
+            newly_completed[0].milestone,
+            OnboardingMilestone::FirstEncryption
+        );
+        assert!(!status.all_completed);
+    }
+}
@@ -0,0 +1,88 @@
+//! Archive names in shared output folders
+//!
+//! Several vaults can be pointed at one external folder, and templates such
+//! as `{date}.age` render the same name for each of them. The cross-vault
+//! output index records which vault wrote each archive there; these are the
+//! rules for naming a new archive when its rendered name is already taken.
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+/// Extension of encrypted archives
+pub const ARCHIVE_EXTENSION: &str = ".age";
+
+/// Template used when a vault has none
+pub const DEFAULT_ARCHIVE_NAME_TEMPLATE: &str = "{vault}";
+
+/// What to do when another vault already has an archive of the same name
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize, specta::Type,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum CrossVaultNamePolicy {
+    /// Add the vault's slug to the new archive's name
+    #[default]
+    Disambiguate,
+    /// Refuse to encrypt with `CrossVaultNameCollision`
+    Fail,
+}
+
+/// Archive file name for a vault's template on `date`
+///
+/// `{vault}` becomes the vault's slug (its sanitized name) and `{date}`
+/// becomes `YYYY-MM-DD`. `.age` is added when the template leaves it out.
+pub fn render_archive_name(template: &str, vault_slug: &str, date: NaiveDate) -> String {
+    let name = template
+        .trim()
+        .replace("{vault}", vault_slug)
+        .replace("{date}", &date.format("%Y-%m-%d").to_string());
+    if name.ends_with(ARCHIVE_EXTENSION) {
+        name
+    } else {
+        format!("{name}{ARCHIVE_EXTENSION}")
+    }
+}
+
+/// `name` with the vault's slug added before the extension
+///
+/// e.g. `2026-03-01.age` → `2026-03-01-Family-Docs.age`
+pub fn with_vault_slug(name: &str, vault_slug: &str) -> String {
+    let stem = name.strip_suffix(ARCHIVE_EXTENSION).unwrap_or(name);
+    format!("{stem}-{vault_slug}{ARCHIVE_EXTENSION}")
+}
+
+/// Name of the shared bundle written beside the backup bundle `name`
+pub fn shared_bundle_name(name: &str) -> String {
+    let stem = name.strip_suffix(ARCHIVE_EXTENSION).unwrap_or(name);
+    format!("{stem}-shared{ARCHIVE_EXTENSION}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_and_disambiguate() {
+        let date = NaiveDate::from_ymd_opt(2026, 3, 1).unwrap();
+
+        assert_eq!(
+            render_archive_name("{date}.age", "Family-Docs", date),
+            "2026-03-01.age"
+        );
+        assert_eq!(
+            render_archive_name(DEFAULT_ARCHIVE_NAME_TEMPLATE, "Family-Docs", date),
+            "Family-Docs.age"
+        );
+        assert_eq!(
+            render_archive_name("{vault}-{date}", "Family-Docs", date),
+            "Family-Docs-2026-03-01.age"
+        );
+
+        let name = with_vault_slug("2026-03-01.age", "Family-Docs");
+        assert_eq!(name, "2026-03-01-Family-Docs.age");
+        assert_eq!(
+            shared_bundle_name(&name),
+            "2026-03-01-Family-Docs-shared.age"
+        );
+    }
+}
@@ -0,0 +1,85 @@
+//! Incomplete archive models
+//!
+//! A crash mid-encryption can leave staged `.partial` files, `.previous`
+//! copies from an interrupted commit, `.age.tmp` leftovers, or a final `.age`
+//! that is empty or has an unreadable header. Such artifacts are moved into a
+//! per-vault quarantine directory with a record of why; nothing there is
+//! deleted except by an explicit purge.
+
+use crate::services::shared::infrastructure::DeleteHonesty;
+use crate::types::ByteSize;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Why an archive file is considered incomplete
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+#[serde(rename_all = "snake_case")]
+pub enum IncompleteArtifactKind {
+    /// Staged output that was never committed over its target
+    StagedPartial,
+    /// Copy of a replaced archive an interrupted commit didn't clean up
+    LeftoverPrevious,
+    /// `.age.tmp` file from an interrupted write
+    TempFile,
+    /// Archive file with no content
+    EmptyArchive,
+    /// Archive whose age header doesn't parse
+    InvalidHeader,
+}
+
+impl IncompleteArtifactKind {
+    pub fn reason(&self) -> &'static str {
+        match self {
+            Self::StagedPartial => "Staged archive was never committed",
+            Self::LeftoverPrevious => "Copy of a replaced archive left by an interrupted commit",
+            Self::TempFile => "Temporary file left by an interrupted write",
+            Self::EmptyArchive => "Archive file is empty",
+            Self::InvalidHeader => "Archive header is unreadable",
+        }
+    }
+}
+
+/// An incomplete artifact found next to a vault's archives
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+pub struct IncompleteArtifact {
+    pub file_name: String,
+    pub kind: IncompleteArtifactKind,
+    pub size: ByteSize,
+    /// Where the file was moved; `None` if it was left in place
+    pub quarantined_as: Option<String>,
+    /// Why the file was left in place (e.g. the archive is immutable)
+    pub skipped_reason: Option<String>,
+}
+
+/// Findings from scanning a vault for incomplete archives
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+pub struct IncompleteArchiveReport {
+    pub vault_id: String,
+    pub artifacts: Vec<IncompleteArtifact>,
+    /// Index entries removed because their archive was quarantined
+    pub removed_index_entries: Vec<String>,
+    pub quarantine_dir: String,
+}
+
+/// Written beside each quarantined file as `<file>.quarantine.json`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QuarantineRecord {
+    pub original_path: String,
+    pub kind: IncompleteArtifactKind,
+    pub reason: String,
+    pub size: u64,
+    pub quarantined_at: DateTime<Utc>,
+}
+
+/// Result of purging a vault's quarantine
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+pub struct QuarantinePurgeReport {
+    pub vault_id: String,
+    /// Quarantined file names that were deleted
+    pub purged: Vec<String>,
+    pub freed: ByteSize,
+    /// Files still in quarantine
+    pub remaining: usize,
+    /// How far the deletion can be trusted; None when nothing was purged
+    pub delete_honesty: Option<DeleteHonesty>,
+}
@@ -0,0 +1,595 @@
+//! Archive retention models
+//!
+//! A vault's retention policy says which archives in its index are worth
+//! keeping: the last N, the newest per day, week, month, or year for a
+//! number of periods (or forever), and anything marked immutable.
+//! Evaluating a policy only classifies archives; nothing is deleted until
+//! the user prunes the eligible set. Archives a migration marked for pruning
+//! are eligible whatever the policy, unless immutable or the newest.
+//!
+//! Periods are calendar periods in UTC, taken from the archives' UTC
+//! timestamps, so the result doesn't depend on the device's time zone.
+//! Weeks are ISO weeks, starting on Monday.
+
+use super::ArchiveIndexEntry;
+use crate::types::ByteSize;
+use chrono::{DateTime, Datelike, Utc};
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+
+/// How many periods a calendar rule keeps
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+#[serde(rename_all = "snake_case")]
+pub enum KeepFor {
+    /// The current period and the ones before it, this many in all
+    Periods(u32),
+    Forever,
+}
+
+impl KeepFor {
+    /// True if a period `age` periods before the current one is kept
+    ///
+    /// Archives dated after now (negative age) are always kept.
+    fn covers(self, age: i64) -> bool {
+        match self {
+            Self::Periods(count) => age < i64::from(count),
+            Self::Forever => true,
+        }
+    }
+}
+
+/// Which archives of a vault to keep
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+#[serde(default)]
+pub struct RetentionPolicy {
+    /// Newest archives kept regardless of date
+    pub keep_last_n: u32,
+    /// Newest archive of each day
+    pub keep_daily_for: Option<KeepFor>,
+    /// Newest archive of each week
+    pub keep_weekly_for: Option<KeepFor>,
+    /// Newest archive of each month
+    pub keep_monthly_for: Option<KeepFor>,
+    /// Newest archive of each year
+    pub keep_yearly_for: Option<KeepFor>,
+    pub always_keep_immutable: bool,
+}
+
+impl Default for RetentionPolicy {
+    fn default() -> Self {
+        Self {
+            keep_last_n: 0,
+            keep_daily_for: None,
+            keep_weekly_for: None,
+            keep_monthly_for: None,
+            keep_yearly_for: None,
+            always_keep_immutable: true,
+        }
+    }
+}
+
+impl RetentionPolicy {
+    /// Refuse policies that keep nothing but the latest archive
+    pub fn validate(&self) -> Result<(), String> {
+        let calendar_rules = [
+            self.keep_daily_for,
+            self.keep_weekly_for,
+            self.keep_monthly_for,
+            self.keep_yearly_for,
+        ];
+        if calendar_rules.contains(&Some(KeepFor::Periods(0))) {
+            return Err("Keep at least one period, or remove the rule".to_string());
+        }
+        if self.keep_last_n == 0 && calendar_rules.iter().all(Option::is_none) {
+            return Err("A retention policy needs at least one keep rule".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Why an archive is kept
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+#[serde(rename_all = "snake_case")]
+pub enum RetentionRule {
+    Immutable,
+    KeepLast,
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+    /// The vault's newest archive, which no policy can prune
+    Latest,
+    /// No policy applies, or it couldn't be evaluated
+    Unevaluated,
+}
+
+/// What a policy says about an archive
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum RetentionDecision {
+    /// Kept by `rule`; the first matching rule is reported
+    Retained {
+        rule: RetentionRule,
+    },
+    PruneEligible,
+}
+
+impl RetentionDecision {
+    pub fn is_prune_eligible(&self) -> bool {
+        matches!(self, Self::PruneEligible)
+    }
+}
+
+/// Decision for one archive
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+pub struct ArchiveRetention {
+    pub archive_id: String,
+    pub archive_name: String,
+    pub created_at: DateTime<Utc>,
+    pub decision: RetentionDecision,
+}
+
+/// Archives a vault's policy would prune
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+pub struct RetentionSummary {
+    pub eligible_count: usize,
+    /// Space freed by pruning them; only archive files that nothing kept
+    /// still refers to count
+    pub reclaimable: ByteSize,
+}
+
+/// A vault's policy applied to its archive index
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+pub struct RetentionEvaluation {
+    pub vault_id: String,
+    pub policy: Option<RetentionPolicy>,
+    /// In index order, oldest first
+    pub archives: Vec<ArchiveRetention>,
+    pub summary: RetentionSummary,
+    /// Set when the policy wasn't applied, e.g. while the clock is unreliable
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub skipped_reason: Option<String>,
+}
+
+impl RetentionEvaluation {
+    /// IDs of the archives the policy would prune
+    pub fn eligible_ids(&self) -> Vec<String> {
+        self.archives
+            .iter()
+            .filter(|archive| archive.decision.is_prune_eligible())
+            .map(|archive| archive.archive_id.clone())
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Period {
+    Day,
+    Week,
+    Month,
+    Year,
+}
+
+impl Period {
+    /// Number of the UTC period holding `at`; consecutive periods differ by one
+    fn index(self, at: DateTime<Utc>) -> i64 {
+        let date = at.date_naive();
+        match self {
+            Self::Day => i64::from(date.num_days_from_ce()),
+            Self::Week => {
+                let monday = date.num_days_from_ce() - date.weekday().num_days_from_monday() as i32;
+                // Day 1 of the common era was a Monday
+                i64::from(monday - 1).div_euclid(7)
+            }
+            Self::Month => i64::from(date.year()) * 12 + i64::from(date.month0()),
+            Self::Year => i64::from(date.year()),
+        }
+    }
+}
+
+/// Newest first; ties broken by revision, then ID, so the order is total
+fn newest_first(a: &ArchiveIndexEntry, b: &ArchiveIndexEntry) -> Ordering {
+    b.created_at
+        .cmp(&a.created_at)
+        .then(b.encryption_revision.cmp(&a.encryption_revision))
+        .then(b.archive_id.cmp(&a.archive_id))
+}
+
+/// Record `rule` for `entry` unless an earlier rule already kept it
+fn keep<'a>(
+    kept: &mut HashMap<&'a str, RetentionRule>,
+    entry: &'a ArchiveIndexEntry,
+    rule: RetentionRule,
+) {
+    kept.entry(entry.archive_id.as_str()).or_insert(rule);
+}
+
+/// A vault's newest archive, which pruning must never remove
+pub fn latest_archive(entries: &[ArchiveIndexEntry]) -> Option<&ArchiveIndexEntry> {
+    entries.iter().min_by(|a, b| newest_first(a, b))
+}
+
+/// Classify each of a vault's archives under `policy` at `now`
+///
+/// Returns one decision per entry, in the order given. The vault's newest
+/// archive is always retained.
+pub fn evaluate_retention(
+    policy: &RetentionPolicy,
+    entries: &[ArchiveIndexEntry],
+    now: DateTime<Utc>,
+) -> Vec<ArchiveRetention> {
+    let mut sorted: Vec<&ArchiveIndexEntry> = entries.iter().collect();
+    sorted.sort_by(|a, b| newest_first(a, b));
+
+    // First rule to keep each archive, by archive ID
+    let mut kept: HashMap<&str, RetentionRule> = HashMap::new();
+
+    if policy.always_keep_immutable {
+        for entry in sorted.iter().filter(|entry| entry.immutable) {
+            keep(&mut kept, entry, RetentionRule::Immutable);
+        }
+    }
+
+    // Archives marked after a migration don't count toward any other rule
+    sorted.retain(|entry| !entry.prune_marked);
+    for entry in sorted.iter().take(policy.keep_last_n as usize) {
+        keep(&mut kept, entry, RetentionRule::KeepLast);
+    }
+
+    let calendar_rules = [
+        (RetentionRule::Daily, Period::Day, policy.keep_daily_for),
+        (RetentionRule::Weekly, Period::Week, policy.keep_weekly_for),
+        (
+            RetentionRule::Monthly,
+            Period::Month,
+            policy.keep_monthly_for,
+        ),
+        (RetentionRule::Yearly, Period::Year, policy.keep_yearly_for),
+    ];
+    for (rule, period, keep_for) in calendar_rules {
+        let Some(keep_for) = keep_for else {
+            continue;
+        };
+        let current = period.index(now);
+        let mut seen = HashSet::new();
+        for entry in &sorted {
+            let index = period.index(entry.created_at);
+            // Only the first (newest) archive of each period counts
+            if seen.insert(index) && keep_for.covers(current - index) {
+                keep(&mut kept, entry, rule);
+            }
+        }
+    }
+
+    if let Some(latest) = latest_archive(entries) {
+        keep(&mut kept, latest, RetentionRule::Latest);
+    }
+
+    entries
+        .iter()
+        .map(|entry| ArchiveRetention {
+            archive_id: entry.archive_id.clone(),
+            archive_name: entry.archive_name.clone(),
+            created_at: entry.created_at,
+            decision: match kept.get(entry.archive_id.as_str()) {
+                Some(&rule) => RetentionDecision::Retained { rule },
+                None => RetentionDecision::PruneEligible,
+            },
+        })
+        .collect()
+}
+
+/// Archives retained as `Unevaluated`, for vaults without a usable policy
+///
+/// Archives marked for pruning after a migration are still eligible.
+pub fn unevaluated_retention(entries: &[ArchiveIndexEntry]) -> Vec<ArchiveRetention> {
+    let latest = latest_archive(entries).map(|entry| entry.archive_id.as_str());
+    entries
+        .iter()
+        .map(|entry| ArchiveRetention {
+            archive_id: entry.archive_id.clone(),
+            archive_name: entry.archive_name.clone(),
+            created_at: entry.created_at,
+            decision: if entry.prune_marked
+                && !entry.immutable
+                && latest != Some(entry.archive_id.as_str())
+            {
+                RetentionDecision::PruneEligible
+            } else {
+                RetentionDecision::Retained {
+                    rule: RetentionRule::Unevaluated,
+                }
+            },
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use RetentionRule::*;
+
+    const ELIGIBLE: Option<RetentionRule> = None;
+
+    fn at(timestamp: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(timestamp)
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    fn entry(id: &str, created_at: &str, revision: u32, immutable: bool) -> ArchiveIndexEntry {
+        ArchiveIndexEntry {
+            archive_id: id.to_string(),
+            vault_id: "vault-001".to_string(),
+            archive_name: "Documents.age".to_string(),
+            encryption_revision: revision,
+            created_at: at(created_at),
+            file_count: 1,
+            comment: None,
+            comment_updated_at: None,
+            immutable,
+            parity: None,
+            migrated_from: None,
+            prune_marked: false,
+            remapped_from: None,
+            archive_sha256: None,
+            recipients: Vec::new(),
+        }
+    }
+
+    struct Case {
+        name: &'static str,
+        policy: RetentionPolicy,
+        now: &'static str,
+        /// (id, created_at, revision, immutable, expected rule or ELIGIBLE)
+        archives: Vec<(&'static str, &'static str, u32, bool, Option<RetentionRule>)>,
+    }
+
+    fn policy(edit: impl FnOnce(&mut RetentionPolicy)) -> RetentionPolicy {
+        let mut policy = RetentionPolicy::default();
+        edit(&mut policy);
+        policy
+    }
+
+    fn cases() -> Vec<Case> {
+        vec![
+            Case {
+                name: "keep last three",
+                policy: policy(|p| p.keep_last_n = 3),
+                now: "2026-03-10T12:00:00Z",
+                archives: vec![
+                    ("a1", "2026-03-01T09:00:00Z", 1, false, ELIGIBLE),
+                    ("a2", "2026-03-02T09:00:00Z", 2, false, ELIGIBLE),
+                    ("a3", "2026-03-03T09:00:00Z", 3, false, Some(KeepLast)),
+                    ("a4", "2026-03-04T09:00:00Z", 4, false, Some(KeepLast)),
+                    ("a5", "2026-03-05T09:00:00Z", 5, false, Some(KeepLast)),
+                ],
+            },
+            Case {
+                name: "daily windows end at UTC midnight",
+                policy: policy(|p| p.keep_daily_for = Some(KeepFor::Periods(2))),
+                now: "2026-03-10T00:30:00Z",
+                archives: vec![
+                    ("a1", "2026-03-08T23:59:59Z", 1, false, ELIGIBLE),
+                    ("a2", "2026-03-09T10:00:00Z", 2, false, ELIGIBLE),
+                    // 2026-03-09T23:59:59Z, written with a local offset
+                    ("a3", "2026-03-10T04:59:59+05:00", 3, false, Some(Daily)),
+                    ("a4", "2026-03-10T00:00:00Z", 4, false, Some(Daily)),
+                ],
+            },
+            Case {
+                name: "weeks start on Monday",
+                policy: policy(|p| p.keep_weekly_for = Some(KeepFor::Periods(1))),
+                now: "2026-03-11T12:00:00Z",
+                archives: vec![
+                    // Sunday, last week
+                    ("a1", "2026-03-08T23:59:59Z", 1, false, ELIGIBLE),
+                    // Monday, this week
+                    ("a2", "2026-03-09T00:00:00Z", 2, false, Some(Weekly)),
+                ],
+            },
+            Case {
+                name: "weeks across a year boundary",
+                policy: policy(|p| p.keep_weekly_for = Some(KeepFor::Periods(2))),
+                now: "2026-01-07T12:00:00Z",
+                archives: vec![
+                    // ISO week 1 of 2026 starts on Monday 2025-12-29
+                    ("a1", "2025-12-28T23:59:59Z", 1, false, ELIGIBLE),
+                    ("a2", "2025-12-29T00:00:00Z", 2, false, Some(Weekly)),
+                    ("a3", "2026-01-05T08:00:00Z", 3, false, Some(Weekly)),
+                ],
+            },
+            Case {
+                name: "monthly for a year, yearly forever",
+                policy: policy(|p| {
+                    p.keep_monthly_for = Some(KeepFor::Periods(12));
+                    p.keep_yearly_for = Some(KeepFor::Forever);
+                }),
+                now: "2026-03-15T12:00:00Z",
+                archives: vec![
+                    ("a1", "2020-06-01T00:00:00Z", 1, false, Some(Yearly)),
+                    ("a2", "2024-12-31T23:59:59Z", 2, false, Some(Yearly)),
+                    ("a3", "2025-01-01T00:00:00Z", 3, false, ELIGIBLE),
+                    ("a4", "2025-03-31T23:59:59Z", 4, false, ELIGIBLE),
+                    ("a5", "2025-04-30T23:59:59Z", 5, false, Some(Monthly)),
+                    ("a6", "2026-03-01T00:00:00Z", 6, false, ELIGIBLE),
+                    ("a7", "2026-03-14T08:00:00Z", 7, false, Some(Monthly)),
+                ],
+            },
+            Case {
+                name: "immutable archives are kept",
+                policy: policy(|p| p.keep_last_n = 1),
+                now: "2026-03-10T12:00:00Z",
+                archives: vec![
+                    ("a1", "2026-03-01T09:00:00Z", 1, true, Some(Immutable)),
+                    ("a2", "2026-03-02T09:00:00Z", 2, false, ELIGIBLE),
+                    ("a3", "2026-03-03T09:00:00Z", 3, true, Some(Immutable)),
+                ],
+            },
+            Case {
+                name: "immutable archives follow the policy when not always kept",
+                policy: policy(|p| {
+                    p.keep_last_n = 1;
+                    p.always_keep_immutable = false;
+                }),
+                now: "2026-03-10T12:00:00Z",
+                archives: vec![
+                    ("a1", "2026-03-01T09:00:00Z", 1, true, ELIGIBLE),
+                    ("a2", "2026-03-02T09:00:00Z", 2, false, Some(KeepLast)),
+                ],
+            },
+            Case {
+                name: "the latest archive outlives every window",
+                policy: policy(|p| p.keep_daily_for = Some(KeepFor::Periods(1))),
+                now: "2026-03-10T12:00:00Z",
+                archives: vec![
+                    ("a1", "2026-01-01T09:00:00Z", 1, false, ELIGIBLE),
+                    ("a2", "2026-01-02T09:00:00Z", 2, false, Some(Latest)),
+                ],
+            },
+            Case {
+                name: "archives dated after now are kept",
+                policy: policy(|p| p.keep_daily_for = Some(KeepFor::Periods(1))),
+                now: "2026-03-10T12:00:00Z",
+                archives: vec![
+                    ("a1", "2026-03-09T09:00:00Z", 1, false, ELIGIBLE),
+                    ("a2", "2026-03-11T09:00:00Z", 2, false, Some(Daily)),
+                    ("a3", "2026-03-12T09:00:00Z", 3, false, Some(Daily)),
+                ],
+            },
+            Case {
+                name: "equal timestamps order by revision",
+                policy: policy(|p| p.keep_last_n = 1),
+                now: "2026-03-10T12:00:00Z",
+                archives: vec![
+                    ("a2", "2026-03-05T09:00:00Z", 2, false, Some(KeepLast)),
+                    ("a1", "2026-03-05T09:00:00Z", 1, false, ELIGIBLE),
+                ],
+            },
+        ]
+    }
+
+    #[test]
+    fn test_evaluate_retention_table() {
+        for case in cases() {
+            let entries: Vec<_> = case
+                .archives
+                .iter()
+                .map(|&(id, created_at, revision, immutable, _)| {
+                    entry(id, created_at, revision, immutable)
+                })
+                .collect();
+
+            let decisions = evaluate_retention(&case.policy, &entries, at(case.now));
+
+            assert_eq!(decisions.len(), entries.len(), "{}", case.name);
+            for (decision, &(id, .., expected)) in decisions.iter().zip(&case.archives) {
+                let expected = match expected {
+                    Some(rule) => RetentionDecision::Retained { rule },
+                    None => RetentionDecision::PruneEligible,
+                };
+                assert_eq!(decision.archive_id, id, "{}", case.name);
+                assert_eq!(decision.decision, expected, "{}: {}", case.name, id);
+            }
+        }
+    }
+
+    #[test]
+    fn test_evaluation_ignores_input_order() {
+        for case in cases() {
+            let mut entries: Vec<_> = case
+                .archives
+                .iter()
+                .map(|&(id, created_at, revision, immutable, _)| {
+                    entry(id, created_at, revision, immutable)
+                })
+                .collect();
+            let now = at(case.now);
+
+            let forward = evaluate_retention(&case.policy, &entries, now);
+            entries.reverse();
+            let mut backward = evaluate_retention(&case.policy, &entries, now);
+            backward.reverse();
+
+            assert_eq!(forward, backward, "{}", case.name);
+        }
+    }
+
+    #[test]
+    fn test_policy_validation() {
+        assert!(RetentionPolicy::default().validate().is_err());
+        assert!(
+            policy(|p| p.keep_monthly_for = Some(KeepFor::Periods(0)))
+                .validate()
+                .is_err()
+        );
+        assert!(policy(|p| p.keep_last_n = 3).validate().is_ok());
+        assert!(
+            policy(|p| p.keep_yearly_for = Some(KeepFor::Forever))
+                .validate()
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn test_policy_json_shape() {
+        let policy: RetentionPolicy = serde_json::from_str(
+            r#"{"keep_monthly_for": {"periods": 12}, "keep_yearly_for": "forever"}"#,
+        )
+        .unwrap();
+        assert_eq!(policy.keep_monthly_for, Some(KeepFor::Periods(12)));
+        assert_eq!(policy.keep_yearly_for, Some(KeepFor::Forever));
+        assert!(policy.always_keep_immutable);
+    }
+
+    #[test]
+    fn test_archives_marked_by_migration_are_eligible() {
+        let mut entries = vec![
+            entry("a1", "2026-03-01T09:00:00Z", 1, false),
+            entry("a2", "2026-03-02T09:00:00Z", 2, true),
+            entry("a3", "2026-03-03T09:00:00Z", 3, false),
+        ];
+        for entry in &mut entries {
+            entry.prune_marked = true;
+        }
+        let decisions = |retention: Vec<ArchiveRetention>| -> Vec<bool> {
+            retention
+                .iter()
+                .map(|archive| archive.decision.is_prune_eligible())
+                .collect()
+        };
+
+        // Kept only as immutable or as the newest archive
+        let keep_all = policy(|p| p.keep_last_n = 10);
+        assert_eq!(
+            decisions(evaluate_retention(
+                &keep_all,
+                &entries,
+                at("2026-03-10T12:00:00Z")
+            )),
+            vec![true, false, false]
+        );
+        assert_eq!(
+            decisions(unevaluated_retention(&entries)),
+            vec![true, false, false]
+        );
+
+        // Unmarked archives count toward the rules in their place
+        entries[1].prune_marked = false;
+        entries[1].immutable = false;
+        let retention = evaluate_retention(
+            &policy(|p| p.keep_last_n = 1),
+            &entries,
+            at("2026-03-10T12:00:00Z"),
+        );
+        assert_eq!(
+            retention[1].decision,
+            RetentionDecision::Retained { rule: KeepLast }
+        );
+        assert_eq!(
+            retention[2].decision,
+            RetentionDecision::Retained { rule: Latest }
+        );
+    }
+}
@@ -0,0 +1,22 @@
+//! Shared vault store status
+//!
+//! OS accounts on one machine can keep their vaults in one shared store.
+//! Each account still has its own vault order, favorites, snoozes and
+//! passphrase cache; the store holds the vault manifests and their archive
+//! index, and a lock naming whoever is changing them.
+
+use crate::services::shared::infrastructure::io::StoreLockOwner;
+use serde::{Deserialize, Serialize};
+
+/// This profile's shared store and who is changing it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+pub struct SharedStoreStatus {
+    /// The store's folder, when this profile uses one
+    pub store_dir: Option<String>,
+    /// Vaults kept in the store
+    pub shared_vault_count: usize,
+    /// Holder of the store lock, if a change is under way
+    pub holder: Option<StoreLockOwner>,
+    /// The holder hasn't been seen lately and can be taken over
+    pub holder_stale: bool,
+}
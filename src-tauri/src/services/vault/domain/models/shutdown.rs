@@ -0,0 +1,56 @@
+//! Shutdown safety models
+//!
+//! Quitting while an operation runs would leave temp files and a missing
+//! archive behind. The UI is told what is still running so it can ask the
+//! user; a forced quit cancels the operations, waits a bounded time for them
+//! to clean up, and leaves a record per vault that the next start reports.
+
+use crate::services::shared::infrastructure::OperationKind;
+use crate::types::DurationMs;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// An operation that would be cut short by quitting now
+#[derive(Debug, Clone, PartialEq, Serialize, specta::Type)]
+pub struct ActiveOperationInfo {
+    pub kind: OperationKind,
+    pub vault_id: Option<String>,
+    pub started_at: DateTime<Utc>,
+    /// Fraction done (0.0 to 1.0), when the operation reports progress
+    pub progress: Option<f32>,
+    pub estimated_time_remaining: Option<DurationMs>,
+}
+
+/// Whether the app can quit without interrupting anything
+#[derive(Debug, Clone, PartialEq, Serialize, specta::Type)]
+pub struct ShutdownCheck {
+    pub safe_to_quit: bool,
+    /// Running operations, oldest first
+    pub operations: Vec<ActiveOperationInfo>,
+    /// Time until the last of them is expected to finish; `None` unless
+    /// every operation has an estimate
+    pub estimated_time_remaining: Option<DurationMs>,
+}
+
+/// What a forced quit cancelled
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, specta::Type)]
+pub struct ForcedQuitReport {
+    pub cancelled_operations: Vec<OperationKind>,
+    /// Whether every operation wound down before the timeout
+    pub stopped_in_time: bool,
+    /// Vaults an interrupted-operation record was written for
+    pub interrupted_vaults: Vec<String>,
+}
+
+/// An operation on a vault that a forced quit cut short
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InterruptedOperation {
+    pub operation: OperationKind,
+    pub interrupted_at: DateTime<Utc>,
+    /// Whether it cleaned up after itself before the app quit
+    pub stopped_in_time: bool,
+    /// Leftover archive files the next start moved to quarantine; `None`
+    /// until that start has checked
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quarantined_files: Option<usize>,
+}
@@ -0,0 +1,284 @@
+//! Vault statistics history models
+//!
+//! A snapshot of a vault's archives is recorded after each change to them,
+//! so the UI can chart how the vault grew. Records are cumulative (each one
+//! describes the whole vault at that moment), which makes downsampling a
+//! matter of picking records rather than summing them.
+
+use crate::services::vault::domain::models::ArchiveIndexEntry;
+use crate::types::ByteSize;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Records kept per vault; the oldest are dropped beyond this
+pub const MAX_HISTORY_RECORDS: usize = 1000;
+
+/// Points returned for a chart when the caller doesn't ask for a number
+pub const DEFAULT_CHART_POINTS: usize = 60;
+
+/// Most points a chart can ask for
+pub const MAX_CHART_POINTS: usize = 365;
+
+/// Window the growth figure covers
+pub const GROWTH_WINDOW_DAYS: i64 = 30;
+
+/// A vault's archives at one moment
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+pub struct StatisticsRecord {
+    pub recorded_at: DateTime<Utc>,
+    /// Archives in the vault's archive index
+    pub archive_count: usize,
+    /// Archive files on disk; several index entries can share one file
+    pub archive_files: usize,
+    /// Size of the archive files on disk
+    pub total_encrypted: ByteSize,
+    /// Files held by the archive files on disk
+    pub total_file_count: usize,
+    pub largest_archive: ByteSize,
+}
+
+impl StatisticsRecord {
+    /// Describe a vault's archives, sizing each archive file with `size_of`
+    ///
+    /// An archive file holds the latest encryption written to it, so only
+    /// that entry's files are counted. Missing files (`size_of` returns
+    /// `None`) are left out.
+    pub fn snapshot(
+        entries: &[ArchiveIndexEntry],
+        size_of: impl Fn(&str) -> Option<u64>,
+        recorded_at: DateTime<Utc>,
+    ) -> Self {
+        let mut current: HashMap<&str, &ArchiveIndexEntry> = HashMap::new();
+        for entry in entries {
+            let newer = current
+                .get(entry.archive_name.as_str())
+                .is_none_or(|existing| entry.created_at >= existing.created_at);
+            if newer {
+                current.insert(&entry.archive_name, entry);
+            }
+        }
+
+        let mut record = Self {
+            recorded_at,
+            archive_count: entries.len(),
+            archive_files: 0,
+            total_encrypted: ByteSize::ZERO,
+            total_file_count: 0,
+            largest_archive: ByteSize::ZERO,
+        };
+        for (name, entry) in current {
+            let Some(size) = size_of(name) else {
+                continue;
+            };
+            record.archive_files += 1;
+            record.total_encrypted += ByteSize(size);
+            record.total_file_count += entry.file_count;
+            record.largest_archive = record.largest_archive.max(ByteSize(size));
+        }
+        record
+    }
+}
+
+/// How far back a history series reaches
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+#[serde(rename_all = "snake_case")]
+pub enum StatisticsRange {
+    Week,
+    #[default]
+    Month,
+    Quarter,
+    Year,
+    All,
+}
+
+impl StatisticsRange {
+    /// Earliest record the range includes at `now` (`None` for everything)
+    pub fn since(self, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let days = match self {
+            Self::Week => 7,
+            Self::Month => 30,
+            Self::Quarter => 90,
+            Self::Year => 365,
+            Self::All => return None,
+        };
+        Some(now - Duration::days(days))
+    }
+}
+
+/// A vault's statistics over a range, ready to chart
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+pub struct VaultStatisticsHistory {
+    pub vault_id: String,
+    pub range: StatisticsRange,
+    /// Oldest first, at most the requested number of points
+    pub points: Vec<StatisticsRecord>,
+    /// Records in the range before downsampling
+    pub total_records: usize,
+}
+
+/// Figures derived from a vault's history for the statistics view
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+pub struct StatisticsTrend {
+    /// Growth over the last 30 days, zero if the vault shrank; `None`
+    /// until the history reaches back that far
+    pub growth_30d: Option<ByteSize>,
+    /// Encrypted size 30 days ago, for telling growth from shrinkage
+    pub size_30d_ago: Option<ByteSize>,
+    /// Average archive file size in the latest record
+    pub average_archive_size: Option<ByteSize>,
+}
+
+impl StatisticsTrend {
+    /// Derive the trend from `records` (oldest first) at `now`
+    pub fn from_records(records: &[StatisticsRecord], now: DateTime<Utc>) -> Self {
+        let Some(latest) = records.last() else {
+            return Self::default();
+        };
+        let cutoff = now - Duration::days(GROWTH_WINDOW_DAYS);
+        let baseline = records
+            .iter()
+            .rev()
+            .find(|record| record.recorded_at <= cutoff)
+            .map(|record| record.total_encrypted);
+
+        Self {
+            growth_30d: baseline.map(|before| {
+                ByteSize(
+                    latest
+                        .total_encrypted
+                        .bytes()
+                        .saturating_sub(before.bytes()),
+                )
+            }),
+            size_30d_ago: baseline,
+            average_archive_size: (latest.archive_files > 0)
+                .then(|| ByteSize(latest.total_encrypted.bytes() / latest.archive_files as u64)),
+        }
+    }
+}
+
+/// At most `max_points` of `records`, spread evenly and always ending with
+/// the latest
+///
+/// Records are split into `max_points` equal runs and the last of each run
+/// is kept, since each record already sums up everything before it.
+pub fn downsample(records: &[StatisticsRecord], max_points: usize) -> Vec<StatisticsRecord> {
+    let len = records.len();
+    if max_points == 0 {
+        return Vec::new();
+    }
+    if len <= max_points {
+        return records.to_vec();
+    }
+    (1..=max_points)
+        .map(|bucket| records[bucket * len / max_points - 1].clone())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(day: i64) -> DateTime<Utc> {
+        "2026-01-01T00:00:00Z".parse::<DateTime<Utc>>().unwrap() + Duration::days(day)
+    }
+
+    fn record(day: i64, total: u64) -> StatisticsRecord {
+        StatisticsRecord {
+            recorded_at: at(day),
+            archive_count: day as usize + 1,
+            archive_files: 2,
+            total_encrypted: ByteSize(total),
+            total_file_count: 10,
+            largest_archive: ByteSize(total / 2),
+        }
+    }
+
+    fn entry(
+        archive_id: &str,
+        archive_name: &str,
+        day: i64,
+        file_count: usize,
+    ) -> ArchiveIndexEntry {
+        ArchiveIndexEntry {
+            archive_id: archive_id.to_string(),
+            vault_id: "vault-001".to_string(),
+            archive_name: archive_name.to_string(),
+            encryption_revision: 1,
+            created_at: at(day),
+            file_count,
+            comment: None,
+            comment_updated_at: None,
+            immutable: false,
+            parity: None,
+            migrated_from: None,
+            prune_marked: false,
+            remapped_from: None,
+            archive_sha256: None,
+            recipients: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_snapshot_counts_the_file_on_disk() {
+        let entries = vec![
+            entry("a1", "Family.age", 0, 3),
+            entry("a2", "Family.age", 5, 4),
+            entry("a3", "Family-2.age", 6, 7),
+            entry("a4", "Gone.age", 7, 9),
+        ];
+        let size_of = |name: &str| match name {
+            "Family.age" => Some(1000),
+            "Family-2.age" => Some(3000),
+            _ => None,
+        };
+
+        let record = StatisticsRecord::snapshot(&entries, size_of, at(8));
+        assert_eq!(record.archive_count, 4);
+        assert_eq!(record.archive_files, 2);
+        assert_eq!(record.total_encrypted, ByteSize(4000));
+        // The newer entry wrote Family.age; the missing archive is left out
+        assert_eq!(record.total_file_count, 11);
+        assert_eq!(record.largest_archive, ByteSize(3000));
+    }
+
+    #[test]
+    fn test_downsample_keeps_last_of_each_run() {
+        let records: Vec<_> = (0..10).map(|day| record(day, day as u64 * 100)).collect();
+
+        let points = downsample(&records, 4);
+        let days: Vec<_> = points.iter().map(|p| p.archive_count - 1).collect();
+        assert_eq!(days, vec![1, 4, 6, 9]);
+
+        assert_eq!(downsample(&records, 10), records);
+        assert_eq!(downsample(&records, 50), records);
+        assert_eq!(downsample(&records, 1), vec![records[9].clone()]);
+        assert!(downsample(&records, 0).is_empty());
+    }
+
+    #[test]
+    fn test_trend() {
+        let records = vec![record(0, 1000), record(20, 1500), record(45, 4000)];
+
+        let trend = StatisticsTrend::from_records(&records, at(50));
+        assert_eq!(trend.size_30d_ago, Some(ByteSize(1500)));
+        assert_eq!(trend.growth_30d, Some(ByteSize(2500)));
+        assert_eq!(trend.average_archive_size, Some(ByteSize(2000)));
+
+        // Not enough history for the growth figure yet
+        let trend = StatisticsTrend::from_records(&records, at(25));
+        assert_eq!(trend.growth_30d, None);
+
+        // A pruned vault reports no growth rather than a negative figure
+        let shrunk = vec![record(0, 4000), record(40, 1000)];
+        let trend = StatisticsTrend::from_records(&shrunk, at(40));
+        assert_eq!(trend.growth_30d, Some(ByteSize::ZERO));
+        assert_eq!(trend.size_30d_ago, Some(ByteSize(4000)));
+
+        assert_eq!(
+            StatisticsTrend::from_records(&[], at(0)),
+            StatisticsTrend::default()
+        );
+    }
+}
@@ -0,0 +1,154 @@
+//! Storage usage models
+//!
+//! The app keeps working data outside the vault archives: staging copies,
+//! quarantined archives, metadata snapshots, caches, logs and trash. Each
+//! category has a quota; when it's exceeded, the least recently used items
+//! that are safe to delete are purged until the category fits again.
+
+use crate::services::shared::infrastructure::path_management::IndexingProtection;
+use crate::types::ByteSize;
+use serde::{Deserialize, Serialize};
+
+const MIB: u64 = 1024 * 1024;
+
+/// A kind of app data tracked for storage usage
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, specta::Type)]
+#[serde(rename_all = "snake_case")]
+pub enum StorageCategory {
+    /// Plaintext copies made while encrypting
+    Staging,
+    /// Incomplete archives moved aside after an interrupted encryption
+    Quarantine,
+    /// Restore points of the registry, manifests and archive index
+    Snapshots,
+    /// Data that can be rebuilt
+    Cache,
+    Logs,
+    /// Deleted items kept for a while before they're gone
+    Trash,
+}
+
+impl StorageCategory {
+    pub const ALL: [Self; 6] = [
+        Self::Staging,
+        Self::Quarantine,
+        Self::Snapshots,
+        Self::Cache,
+        Self::Logs,
+        Self::Trash,
+    ];
+
+    /// What can be purged when the category is over quota
+    pub fn purge_rule(&self) -> &'static str {
+        match self {
+            Self::Staging => "Leftovers of interrupted operations; never a running one",
+            Self::Quarantine => "Files quarantined more than 30 days ago",
+            Self::Snapshots => "All but the newest snapshot, oldest first",
+            Self::Cache => "Everything, least recently used first",
+            Self::Logs => "Old log files; never the current one",
+            Self::Trash => "Items deleted more than 30 days ago",
+        }
+    }
+}
+
+/// Size limit of each category
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+#[serde(default)]
+pub struct StorageQuotas {
+    pub staging: ByteSize,
+    pub quarantine: ByteSize,
+    pub snapshots: ByteSize,
+    pub cache: ByteSize,
+    pub logs: ByteSize,
+    pub trash: ByteSize,
+}
+
+impl Default for StorageQuotas {
+    fn default() -> Self {
+        Self {
+            staging: ByteSize(2048 * MIB),
+            quarantine: ByteSize(2048 * MIB),
+            snapshots: ByteSize(256 * MIB),
+            cache: ByteSize(512 * MIB),
+            logs: ByteSize(100 * MIB),
+            trash: ByteSize(1024 * MIB),
+        }
+    }
+}
+
+impl StorageQuotas {
+    pub fn quota(&self, category: StorageCategory) -> ByteSize {
+        match category {
+            StorageCategory::Staging => self.staging,
+            StorageCategory::Quarantine => self.quarantine,
+            StorageCategory::Snapshots => self.snapshots,
+            StorageCategory::Cache => self.cache,
+            StorageCategory::Logs => self.logs,
+            StorageCategory::Trash => self.trash,
+        }
+    }
+}
+
+/// Usage of one category
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+pub struct CategoryUsage {
+    pub category: StorageCategory,
+    pub path: String,
+    /// Everything in the category's directory
+    pub size: ByteSize,
+    pub item_count: usize,
+    /// Space freed by purging every eligible item
+    pub purge_eligible: ByteSize,
+    pub eligible_items: usize,
+    /// Which items are eligible for purging
+    pub purge_rule: String,
+    pub quota: ByteSize,
+    pub over_quota: bool,
+}
+
+/// Storage used by the app outside the vault archives
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+pub struct StorageUsageReport {
+    pub categories: Vec<CategoryUsage>,
+    pub total: ByteSize,
+    pub total_purge_eligible: ByteSize,
+}
+
+/// An item deleted by quota cleanup
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+pub struct PurgedStorageItem {
+    pub category: StorageCategory,
+    /// File or directory name; the snapshot ID for snapshots
+    pub name: String,
+    pub freed: ByteSize,
+}
+
+/// Result of a quota cleanup pass
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+pub struct StorageCleanupReport {
+    pub purged: Vec<PurgedStorageItem>,
+    pub freed: ByteSize,
+    /// Categories still over quota once nothing more could be purged
+    pub still_over_quota: Vec<StorageCategory>,
+}
+
+/// A directory that holds keys or plaintext
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, specta::Type)]
+#[serde(rename_all = "snake_case")]
+pub enum SensitiveDirectoryKind {
+    Keys,
+    Staging,
+    /// One vault's quarantine
+    Quarantine,
+    /// Decrypted output awaiting its cleanup session
+    DecryptedSession,
+}
+
+/// Whether a sensitive directory is kept out of the search index
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, specta::Type)]
+pub struct SensitiveDirectoryStatus {
+    pub kind: SensitiveDirectoryKind,
+    pub path: String,
+    /// `None` while the directory doesn't exist
+    pub protection: Option<IndexingProtection>,
+}
@@ -3,6 +3,8 @@
 //! Implements the vault-centric architecture where vaults own keys
 //! and support multiple unlock methods (1 passphrase + up to 3 YubiKeys).
 
+use super::VaultSyncStatus;
+use crate::types::{ByteSize, FormatHints};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
@@ -50,11 +52,15 @@ pub struct EncryptedArchive {
     /// Total number of files in the archive
     pub total_files: u64,
 
-    /// Human-readable total size
-    pub total_size: String,
+    /// Total size of the archived files
+    pub total_size: ByteSize,
 
     /// List of files/directories in the archive
     pub contents: Vec<ArchiveContent>,
+
+    /// Display form of `total_size`
+    #[serde(default)]
+    pub format_hints: Option<FormatHints>,
 }
 
 /// Information about a file/directory in an encrypted archive
@@ -63,11 +69,15 @@ pub struct ArchiveContent {
     /// File or directory name
     pub file: String,
 
-    /// Human-readable size
-    pub size: String,
+    /// Size of the file or directory
+    pub size: ByteSize,
 
     /// SHA-256 hash of the file content
     pub hash: String,
+
+    /// Display form of `size`
+    #[serde(default)]
+    pub format_hints: Option<FormatHints>,
 }
 
 /// Summary information about a vault (for listing)
@@ -78,6 +88,15 @@ pub struct VaultSummary {
     pub description: Option<String>,
     pub created_at: DateTime<Utc>,
     pub key_count: usize,
+    /// Pinned to the top of the vault list (filled in by `list_vaults`)
+    #[serde(default)]
+    pub favorite: bool,
+    /// Position in the user's vault order (filled in by `list_vaults`)
+    #[serde(default)]
+    pub sort_index: u32,
+    /// Whether another device's copy conflicts (filled in by `list_vaults`)
+    #[serde(default)]
+    pub sync_status: VaultSyncStatus,
 }
 
 impl Vault {
@@ -142,6 +161,9 @@ pub fn to_summary(&self) -> VaultSummary {
             description: self.description.clone(),
             created_at: self.created_at,
             key_count: self.keys.len(),
+            favorite: false,
+            sort_index: 0,
+            sync_status: VaultSyncStatus::InSync,
         }
     }
 
@@ -0,0 +1,203 @@
+//! Vault generations
+//!
+//! Every write of a vault's manifest, and of its entries in the archive
+//! index, carries a generation one above the file's last and names the
+//! device that wrote it. Each device remembers the last generation it wrote
+//! to each file. Finding that file at a generation no higher than ours but
+//! written by another device means a sync service replaced our write with a
+//! copy made elsewhere, so the vault is suspected to be in conflict.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// The device that last wrote a manifest or a vault's index entries
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+pub struct LastWriter {
+    /// This profile's device ID (see `AppConfig::device_id`)
+    pub device_id: String,
+    pub hostname: String,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// A file's generation and who wrote it
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+pub struct GenerationStamp {
+    /// 0 for files written before generations were recorded
+    #[serde(default)]
+    pub generation: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_writer: Option<LastWriter>,
+}
+
+impl GenerationStamp {
+    /// Whether `device_id` wrote this
+    pub fn written_by(&self, device_id: &str) -> bool {
+        self.last_writer
+            .as_ref()
+            .is_some_and(|writer| writer.device_id == device_id)
+    }
+
+    /// Whether this stamp, found on disk, replaced the write at generation
+    /// `written` that `device_id` made
+    pub fn hides_write(&self, written: u64, device_id: &str) -> bool {
+        written > 0 && self.generation <= written && !self.written_by(device_id)
+    }
+
+    /// The stamp for `writer`'s next write over this one
+    ///
+    /// Never lower than `written`, the last generation the writer made, so
+    /// a file restored to an older copy can't repeat a generation.
+    pub fn next(&self, written: u64, writer: LastWriter) -> Self {
+        Self {
+            generation: self.generation.max(written) + 1,
+            last_writer: Some(writer),
+        }
+    }
+}
+
+/// File a generation belongs to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, specta::Type)]
+#[serde(rename_all = "snake_case")]
+pub enum GenerationFile {
+    Manifest,
+    ArchiveIndex,
+}
+
+/// Last generations this device wrote for one vault
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WrittenGenerations {
+    #[serde(default)]
+    pub manifest: u64,
+    #[serde(default)]
+    pub archive_index: u64,
+}
+
+impl WrittenGenerations {
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+
+    pub fn get(&self, file: GenerationFile) -> u64 {
+        match file {
+            GenerationFile::Manifest => self.manifest,
+            GenerationFile::ArchiveIndex => self.archive_index,
+        }
+    }
+
+    /// Remember writing `generation`; an older one never lowers the record
+    pub fn record(&mut self, file: GenerationFile, generation: u64) {
+        let written = match file {
+            GenerationFile::Manifest => &mut self.manifest,
+            GenerationFile::ArchiveIndex => &mut self.archive_index,
+        };
+        *written = (*written).max(generation);
+    }
+}
+
+/// Why a vault is suspected to be in conflict
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ConflictEvidence {
+    /// The file holds another device's write at or below this device's last
+    HiddenWrite {
+        file: GenerationFile,
+        on_disk: u64,
+        last_written: u64,
+        writer: Option<LastWriter>,
+    },
+    /// A sync service left a conflicting copy beside the file
+    ConflictedCopy { file: GenerationFile, path: String },
+}
+
+/// Whether a vault's files agree across devices
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+#[serde(rename_all = "snake_case")]
+pub enum VaultSyncStatus {
+    #[default]
+    InSync,
+    /// Changes are blocked until `resolve_vault_conflict`
+    ConflictSuspected,
+}
+
+/// A vault suspected to be in conflict and why
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, specta::Type)]
+pub struct VaultConflict {
+    pub vault_id: String,
+    pub evidence: Vec<ConflictEvidence>,
+}
+
+/// How to settle a suspected conflict
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+#[serde(rename_all = "snake_case")]
+pub enum ConflictStrategy {
+    /// Keep the newest copies this device wrote
+    PreferLocal,
+    /// Keep the newest copies another device wrote
+    PreferRemote,
+    /// Keep the newest manifest and every archive entry that still checks
+    /// out against its archive file
+    MergeIndexes,
+}
+
+/// What resolving a conflict kept and removed
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, specta::Type)]
+pub struct ConflictResolution {
+    pub vault_id: String,
+    pub strategy: ConflictStrategy,
+    /// Generation the resolved manifest was written at
+    pub manifest_generation: u64,
+    /// Generation the resolved index entries were written at
+    pub archive_index_generation: u64,
+    /// Archive entries the vault has now
+    pub archive_count: usize,
+    /// Archive names whose entries were left out (file missing or changed)
+    pub dropped_archives: Vec<String>,
+    /// Conflicting copies removed or emptied
+    pub removed_copies: Vec<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn writer(device_id: &str) -> LastWriter {
+        LastWriter {
+            device_id: device_id.to_string(),
+            hostname: format!("{device_id}-host"),
+            timestamp: Utc::now(),
+        }
+    }
+
+    fn stamp(generation: u64, device_id: &str) -> GenerationStamp {
+        GenerationStamp {
+            generation,
+            last_writer: Some(writer(device_id)),
+        }
+    }
+
+    #[test]
+    fn test_only_another_devices_stamp_at_or_below_ours_hides_a_write() {
+        // Never written here: nothing to hide
+        assert!(!stamp(1, "laptop").hides_write(0, "desktop"));
+        // Our own older write, e.g. after a failed save
+        assert!(!stamp(2, "desktop").hides_write(3, "desktop"));
+        // Another device moved on from what we wrote
+        assert!(!stamp(4, "laptop").hides_write(3, "desktop"));
+
+        assert!(stamp(3, "laptop").hides_write(3, "desktop"));
+        assert!(stamp(2, "laptop").hides_write(3, "desktop"));
+        assert!(GenerationStamp::default().hides_write(3, "desktop"));
+    }
+
+    #[test]
+    fn test_next_generation_passes_both_disk_and_our_last_write() {
+        assert_eq!(stamp(4, "laptop").next(2, writer("desktop")).generation, 5);
+        assert_eq!(stamp(1, "laptop").next(6, writer("desktop")).generation, 7);
+
+        let mut written = WrittenGenerations::default();
+        written.record(GenerationFile::Manifest, 5);
+        written.record(GenerationFile::Manifest, 3);
+        assert_eq!(written.get(GenerationFile::Manifest), 5);
+        assert_eq!(written.get(GenerationFile::ArchiveIndex), 0);
+    }
+}
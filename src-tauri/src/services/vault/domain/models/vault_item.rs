@@ -0,0 +1,360 @@
+//! Vault item models
+//!
+//! Items give a vault structure beyond raw files: "wallet descriptor",
+//! "hardware wallet PIN location", "lawyer contact". Each item may link to
+//! files in the latest archive's manifest and to archives in the index, or
+//! simply carry a short note. Items are stored in the vault manifest, which
+//! is unencrypted on this device, so notes must never hold secrets.
+
+use crate::services::vault::domain::models::contains_secret_marker;
+use crate::services::vault::domain::{VaultError, VaultResult};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+/// Longest allowed item title, in characters
+pub const MAX_ITEM_TITLE_CHARS: usize = 120;
+
+/// Longest allowed item note, in characters
+pub const MAX_ITEM_NOTE_CHARS: usize = 1000;
+
+/// What kind of information an item represents
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, specta::Type)]
+#[serde(rename_all = "snake_case")]
+pub enum VaultItemCategory {
+    WalletDescriptor,
+    WalletBackup,
+    SeedBackupLocation,
+    HardwareWalletPinLocation,
+    RecoveryInstructions,
+    LegalDocument,
+    Insurance,
+    IdentityDocument,
+    PropertyRecord,
+    PasswordManager,
+    RecoveryCodes,
+    Contact,
+    /// Anything else; the title says what it is
+    Other,
+}
+
+/// A structured entry in a vault's inheritance checklist
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+pub struct VaultItem {
+    pub id: String,
+    pub category: VaultItemCategory,
+    pub title: String,
+    /// Relative paths of files in the latest archive's manifest
+    #[serde(default)]
+    pub linked_paths: Vec<String>,
+    /// Archive index IDs this item refers to
+    #[serde(default)]
+    pub linked_archive_ids: Vec<String>,
+    /// Short plaintext note (stored unencrypted)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+    pub completed: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// User-editable fields of an item (used for both add and update)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+pub struct VaultItemInput {
+    pub category: VaultItemCategory,
+    pub title: String,
+    #[serde(default)]
+    pub linked_paths: Vec<String>,
+    #[serde(default)]
+    pub linked_archive_ids: Vec<String>,
+    #[serde(default)]
+    pub note: Option<String>,
+    #[serde(default)]
+    pub completed: bool,
+}
+
+/// An item with the links that no longer resolve
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+pub struct VaultItemView {
+    pub item: VaultItem,
+    /// Linked paths missing from the latest archive's manifest
+    pub missing_paths: Vec<String>,
+    /// Linked archives missing from the archive index
+    pub missing_archive_ids: Vec<String>,
+}
+
+/// Item completion summary for a vault
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+pub struct VaultItemStatistics {
+    pub total_items: usize,
+    pub completed_items: usize,
+    /// Items with at least one link that no longer resolves
+    pub items_with_missing_links: usize,
+    /// Completed share of all items, rounded down (0 when there are none)
+    pub completion_percent: u8,
+}
+
+/// Files and archives that item links may point at
+#[derive(Debug, Default)]
+pub struct ItemLinkTargets<'a> {
+    paths: HashSet<&'a str>,
+    /// `None` when the archive index is unknown; archive links aren't checked
+    archive_ids: Option<HashSet<&'a str>>,
+}
+
+impl<'a> ItemLinkTargets<'a> {
+    pub fn new<P, A>(paths: P, archive_ids: A) -> Self
+    where
+        P: IntoIterator<Item = &'a str>,
+        A: IntoIterator<Item = &'a str>,
+    {
+        Self {
+            paths: paths.into_iter().collect(),
+            archive_ids: Some(archive_ids.into_iter().collect()),
+        }
+    }
+
+    /// Targets that only check file links
+    pub fn paths_only<P>(paths: P) -> Self
+    where
+        P: IntoIterator<Item = &'a str>,
+    {
+        Self {
+            paths: paths.into_iter().collect(),
+            archive_ids: None,
+        }
+    }
+
+    pub fn missing_paths(&self, linked_paths: &[String]) -> Vec<String> {
+        linked_paths
+            .iter()
+            .filter(|path| !self.paths.contains(path.as_str()))
+            .cloned()
+            .collect()
+    }
+
+    pub fn missing_archive_ids(&self, linked_archive_ids: &[String]) -> Vec<String> {
+        let Some(archive_ids) = &self.archive_ids else {
+            return Vec::new();
+        };
+        linked_archive_ids
+            .iter()
+            .filter(|id| !archive_ids.contains(id.as_str()))
+            .cloned()
+            .collect()
+    }
+
+    /// Pair an item with its unresolved links
+    pub fn view(&self, item: &VaultItem) -> VaultItemView {
+        VaultItemView {
+            item: item.clone(),
+            missing_paths: self.missing_paths(&item.linked_paths),
+            missing_archive_ids: self.missing_archive_ids(&item.linked_archive_ids),
+        }
+    }
+}
+
+impl VaultItemInput {
+    /// Trim and check user-supplied fields
+    ///
+    /// Blank notes become `None` and duplicate links are dropped. Titles must
+    /// be non-empty; over-long text and notes that look like key material are
+    /// rejected.
+    pub fn normalize(self) -> VaultResult<Self> {
+        let title = self.title.trim().to_string();
+        if title.is_empty() {
+            return Err(VaultError::InvalidOperation(
+                "Item title cannot be empty".to_string(),
+            ));
+        }
+        if title.chars().count() > MAX_ITEM_TITLE_CHARS {
+            return Err(VaultError::InvalidOperation(format!(
+                "Item title must be at most {} characters",
+                MAX_ITEM_TITLE_CHARS
+            )));
+        }
+
+        let note = self
+            .note
+            .map(|n| n.trim().to_string())
+            .filter(|n| !n.is_empty());
+        if let Some(note) = &note {
+            if note.chars().count() > MAX_ITEM_NOTE_CHARS {
+                return Err(VaultError::InvalidOperation(format!(
+                    "Item note must be at most {} characters",
+                    MAX_ITEM_NOTE_CHARS
+                )));
+            }
+            if contains_secret_marker(note) {
+                return Err(VaultError::InvalidOperation(
+                    "Item notes are stored unencrypted and must not contain key material"
+                        .to_string(),
+                ));
+            }
+        }
+
+        Ok(Self {
+            category: self.category,
+            title,
+            linked_paths: dedup(self.linked_paths),
+            linked_archive_ids: dedup(self.linked_archive_ids),
+            note,
+            completed: self.completed,
+        })
+    }
+}
+
+impl VaultItem {
+    /// New item from normalized input
+    pub fn new(input: VaultItemInput, now: DateTime<Utc>) -> Self {
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            category: input.category,
+            title: input.title,
+            linked_paths: input.linked_paths,
+            linked_archive_ids: input.linked_archive_ids,
+            note: input.note,
+            completed: input.completed,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    /// Replace the editable fields with normalized input
+    pub fn apply(&mut self, input: VaultItemInput, now: DateTime<Utc>) {
+        self.category = input.category;
+        self.title = input.title;
+        self.linked_paths = input.linked_paths;
+        self.linked_archive_ids = input.linked_archive_ids;
+        self.note = input.note;
+        self.completed = input.completed;
+        self.updated_at = now;
+    }
+}
+
+/// Completion counts for a set of items
+pub fn item_statistics(items: &[VaultItem], targets: &ItemLinkTargets) -> VaultItemStatistics {
+    let total_items = items.len();
+    let completed_items = items.iter().filter(|item| item.completed).count();
+    let items_with_missing_links = items
+        .iter()
+        .filter(|item| {
+            !targets.missing_paths(&item.linked_paths).is_empty()
+                || !targets
+                    .missing_archive_ids(&item.linked_archive_ids)
+                    .is_empty()
+        })
+        .count();
+    let completion_percent = if total_items == 0 {
+        0
+    } else {
+        (completed_items * 100 / total_items) as u8
+    };
+
+    VaultItemStatistics {
+        total_items,
+        completed_items,
+        items_with_missing_links,
+        completion_percent,
+    }
+}
+
+fn dedup(values: Vec<String>) -> Vec<String> {
+    let mut seen = HashSet::new();
+    values
+        .into_iter()
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty() && seen.insert(v.clone()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn input(title: &str) -> VaultItemInput {
+        VaultItemInput {
+            category: VaultItemCategory::WalletDescriptor,
+            title: title.to_string(),
+            linked_paths: vec![],
+            linked_archive_ids: vec![],
+            note: None,
+            completed: false,
+        }
+    }
+
+    fn item(completed: bool, linked_paths: &[&str]) -> VaultItem {
+        let mut input = input("Item");
+        input.completed = completed;
+        input.linked_paths = linked_paths.iter().map(|p| p.to_string()).collect();
+        VaultItem::new(input, Utc::now())
+    }
+
+    #[test]
+    fn test_normalize_trims_and_dedups() {
+        let mut raw = input("  Descriptor  ");
+        raw.linked_paths = vec!["a.txt".into(), " a.txt".into(), "".into(), "b.txt".into()];
+        raw.note = Some("   ".into());
+
+        let normalized = raw.normalize().unwrap();
+        assert_eq!(normalized.title, "Descriptor");
+        assert_eq!(normalized.linked_paths, vec!["a.txt", "b.txt"]);
+        assert_eq!(normalized.note, None);
+    }
+
+    #[test]
+    fn test_normalize_rejects_bad_input() {
+        assert!(input(" ").normalize().is_err());
+        assert!(
+            input(&"x".repeat(MAX_ITEM_TITLE_CHARS + 1))
+                .normalize()
+                .is_err()
+        );
+
+        let mut secret = input("PIN");
+        secret.note = Some("age-secret-key-1abc".into());
+        assert!(matches!(
+            secret.normalize(),
+            Err(VaultError::InvalidOperation(_))
+        ));
+    }
+
+    #[test]
+    fn test_missing_links() {
+        let targets = ItemLinkTargets::new(["a.txt"], ["archive-1"]);
+        let mut linked = item(false, &["a.txt", "gone.txt"]);
+        linked.linked_archive_ids = vec!["archive-1".into(), "archive-2".into()];
+
+        let view = targets.view(&linked);
+        assert_eq!(view.missing_paths, vec!["gone.txt"]);
+        assert_eq!(view.missing_archive_ids, vec!["archive-2"]);
+
+        let unknown_index = ItemLinkTargets::paths_only(["a.txt"]);
+        assert!(
+            unknown_index
+                .missing_archive_ids(&linked.linked_archive_ids)
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_item_statistics_math() {
+        let targets = ItemLinkTargets::new(["a.txt"], []);
+        let items = vec![
+            item(true, &["a.txt"]),
+            item(true, &["gone.txt"]),
+            item(false, &[]),
+        ];
+
+        let stats = item_statistics(&items, &targets);
+        assert_eq!(stats.total_items, 3);
+        assert_eq!(stats.completed_items, 2);
+        assert_eq!(stats.items_with_missing_links, 1);
+        assert_eq!(stats.completion_percent, 66);
+
+        assert_eq!(
+            item_statistics(&[], &targets),
+            VaultItemStatistics::default()
+        );
+    }
+}
@@ -0,0 +1,290 @@
+//! Vault key storage risk models
+//!
+//! A vault with "two keys" is no safer than one if both live in the same
+//! laptop bag. Each key is classified by where it physically lives, and a few
+//! heuristic rules flag vaults whose keys could all be lost together.
+//! Recommendations are returned as localization keys.
+
+use crate::services::shared::infrastructure::io::CloudSyncProvider;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Where a key physically lives
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, specta::Type)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum KeyLocus {
+    /// Key file on this machine's own disk
+    ThisMachine,
+    /// Key file on a removable volume
+    RemovableVolume { label: String },
+    /// Hardware token holding the private key
+    HardwareToken { serial: String },
+    /// Not in this device's registry, or someone else's key
+    Unknown,
+}
+
+/// Storage of one of a vault's keys
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, specta::Type)]
+pub struct KeyStorage {
+    pub key_id: String,
+    pub label: String,
+    pub locus: KeyLocus,
+    /// Cloud-sync client whose folder holds the key file
+    pub cloud_sync: Option<CloudSyncProvider>,
+}
+
+/// How serious a finding is (ordered low to high)
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, specta::Type,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum RiskSeverity {
+    Low,
+    Medium,
+    High,
+}
+
+/// Heuristic a finding comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, specta::Type)]
+#[serde(rename_all = "snake_case")]
+pub enum RiskRule {
+    /// Every key lives in the same place
+    SinglePointOfFailure,
+    /// No key is a hardware token
+    NoHardwareToken,
+    /// A passphrase key file sits in a cloud-synced folder
+    KeyFileInCloudSync,
+}
+
+impl RiskRule {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::SinglePointOfFailure => "single_point_of_failure",
+            Self::NoHardwareToken => "no_hardware_token",
+            Self::KeyFileInCloudSync => "key_file_in_cloud_sync",
+        }
+    }
+
+    pub fn severity(&self) -> RiskSeverity {
+        match self {
+            Self::SinglePointOfFailure => RiskSeverity::High,
+            Self::KeyFileInCloudSync => RiskSeverity::Medium,
+            Self::NoHardwareToken => RiskSeverity::Low,
+        }
+    }
+
+    /// Localization key for the recommendation
+    pub fn recommendation_key(&self) -> String {
+        format!("vault_risk.{}", self.as_str())
+    }
+}
+
+/// One risk found in a vault's key storage
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+pub struct RiskFinding {
+    pub rule: RiskRule,
+    pub severity: RiskSeverity,
+    pub recommendation_key: String,
+    pub params: BTreeMap<String, String>,
+    /// Keys the finding is about
+    pub key_ids: Vec<String>,
+}
+
+impl RiskFinding {
+    fn new(rule: RiskRule, key_ids: Vec<String>) -> Self {
+        Self {
+            rule,
+            severity: rule.severity(),
+            recommendation_key: rule.recommendation_key(),
+            params: BTreeMap::new(),
+            key_ids,
+        }
+    }
+}
+
+/// Key storage risks of a vault
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, specta::Type)]
+pub struct VaultRiskAssessment {
+    pub vault_id: String,
+    /// The vault's usable keys
+    pub keys: Vec<KeyStorage>,
+    /// Most severe first
+    pub findings: Vec<RiskFinding>,
+    /// Losing one place loses every key
+    pub single_point_of_failure: bool,
+    pub assessed_at: DateTime<Utc>,
+}
+
+/// Apply every risk rule to a vault's keys
+///
+/// A vault without keys has no findings; keys of unknown locus never count
+/// as sharing a place.
+pub fn assess_key_storage(keys: &[KeyStorage]) -> Vec<RiskFinding> {
+    let mut findings = Vec::new();
+    let Some(first) = keys.first() else {
+        return findings;
+    };
+    let all_ids = || keys.iter().map(|k| k.key_id.clone()).collect::<Vec<_>>();
+
+    if first.locus != KeyLocus::Unknown && keys.iter().all(|k| k.locus == first.locus) {
+        let mut finding = RiskFinding::new(RiskRule::SinglePointOfFailure, all_ids());
+        finding
+            .params
+            .insert("locus".to_string(), locus_name(&first.locus));
+        findings.push(finding);
+    }
+
+    let synced: Vec<&KeyStorage> = keys.iter().filter(|k| k.cloud_sync.is_some()).collect();
+    if !synced.is_empty() {
+        let mut providers: Vec<&str> = synced
+            .iter()
+            .filter_map(|k| k.cloud_sync.map(CloudSyncProvider::name))
+            .collect();
+        providers.sort_unstable();
+        providers.dedup();
+        let mut finding = RiskFinding::new(
+            RiskRule::KeyFileInCloudSync,
+            synced.iter().map(|k| k.key_id.clone()).collect(),
+        );
+        finding
+            .params
+            .insert("providers".to_string(), providers.join(", "));
+        findings.push(finding);
+    }
+
+    if !keys
+        .iter()
+        .any(|k| matches!(k.locus, KeyLocus::HardwareToken { .. }))
+    {
+        findings.push(RiskFinding::new(RiskRule::NoHardwareToken, all_ids()));
+    }
+
+    findings.sort_by(|a, b| b.severity.cmp(&a.severity));
+    findings
+}
+
+fn locus_name(locus: &KeyLocus) -> String {
+    match locus {
+        KeyLocus::ThisMachine => "this_machine".to_string(),
+        KeyLocus::RemovableVolume { label } => label.clone(),
+        KeyLocus::HardwareToken { serial } => serial.clone(),
+        KeyLocus::Unknown => "unknown".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(id: &str, locus: KeyLocus, cloud_sync: Option<CloudSyncProvider>) -> KeyStorage {
+        KeyStorage {
+            key_id: id.to_string(),
+            label: id.to_string(),
+            locus,
+            cloud_sync,
+        }
+    }
+
+    fn usb(label: &str) -> KeyLocus {
+        KeyLocus::RemovableVolume {
+            label: label.to_string(),
+        }
+    }
+
+    fn token(serial: &str) -> KeyLocus {
+        KeyLocus::HardwareToken {
+            serial: serial.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_rules_fire_exactly_when_intended() {
+        use KeyLocus::{ThisMachine, Unknown};
+        use RiskRule::*;
+
+        let cases: Vec<(&str, Vec<KeyStorage>, Vec<RiskRule>)> = vec![
+            ("no keys", vec![], vec![]),
+            (
+                "one laptop key",
+                vec![key("a", ThisMachine, None)],
+                vec![SinglePointOfFailure, NoHardwareToken],
+            ),
+            (
+                "one token",
+                vec![key("a", token("111"), None)],
+                vec![SinglePointOfFailure],
+            ),
+            (
+                "two keys on one USB stick",
+                vec![key("a", usb("KEYS"), None), key("b", usb("KEYS"), None)],
+                vec![SinglePointOfFailure, NoHardwareToken],
+            ),
+            (
+                "laptop key and token",
+                vec![key("a", ThisMachine, None), key("b", token("111"), None)],
+                vec![],
+            ),
+            (
+                "laptop and USB keys",
+                vec![key("a", ThisMachine, None), key("b", usb("KEYS"), None)],
+                vec![NoHardwareToken],
+            ),
+            (
+                "two different tokens",
+                vec![key("a", token("111"), None), key("b", token("222"), None)],
+                vec![],
+            ),
+            (
+                "unknown keys never share a place",
+                vec![key("a", Unknown, None), key("b", Unknown, None)],
+                vec![NoHardwareToken],
+            ),
+            (
+                "synced key file beside a token",
+                vec![
+                    key("a", ThisMachine, Some(CloudSyncProvider::Dropbox)),
+                    key("b", token("111"), None),
+                ],
+                vec![KeyFileInCloudSync],
+            ),
+            (
+                "single synced key file",
+                vec![key("a", ThisMachine, Some(CloudSyncProvider::OneDrive))],
+                vec![SinglePointOfFailure, KeyFileInCloudSync, NoHardwareToken],
+            ),
+        ];
+
+        for (name, keys, expected) in cases {
+            let rules: Vec<RiskRule> = assess_key_storage(&keys)
+                .into_iter()
+                .map(|f| f.rule)
+                .collect();
+            assert_eq!(rules, expected, "case: {}", name);
+        }
+    }
+
+    #[test]
+    fn test_findings_carry_keys_severity_and_recommendation() {
+        let keys = vec![
+            key("a", usb("KEYS"), Some(CloudSyncProvider::Dropbox)),
+            key("b", usb("KEYS"), None),
+        ];
+        let findings = assess_key_storage(&keys);
+
+        let single = &findings[0];
+        assert_eq!(single.severity, RiskSeverity::High);
+        assert_eq!(
+            single.recommendation_key,
+            "vault_risk.single_point_of_failure"
+        );
+        assert_eq!(single.params["locus"], "KEYS");
+        assert_eq!(single.key_ids, ["a", "b"]);
+
+        let synced = &findings[1];
+        assert_eq!(synced.rule, RiskRule::KeyFileInCloudSync);
+        assert_eq!(synced.key_ids, ["a"]);
+        assert_eq!(synced.params["providers"], "Dropbox");
+        assert_eq!(findings[2].severity, RiskSeverity::Low);
+    }
+}
@@ -1,4 +1,5 @@
 use super::super::errors::{VaultError, VaultResult};
+use super::name_validator::{NameKind, NameValidator};
 
 /// Business rules for vault operations
 pub struct VaultRules;
@@ -14,29 +15,11 @@ impl VaultRules {
     pub const MAX_PASSPHRASE_KEYS_PER_VAULT: usize = 1;
 
     /// Validate vault name according to business rules
+    ///
+    /// See [`NameValidator`] for the rules; use [`NameValidator::normalize`]
+    /// to get the name to store.
     pub fn validate_vault_name(name: &str) -> VaultResult<()> {
-        let trimmed = name.trim();
-
-        if trimmed.is_empty() {
-            return Err(VaultError::InvalidName(
-                "Vault name cannot be empty".to_string(),
-            ));
-        }
-
-        if trimmed.len() > 100 {
-            return Err(VaultError::InvalidName(
-                "Vault name must be less than 100 characters".to_string(),
-            ));
-        }
-
-        // Check for invalid characters that might cause file system issues
-        if trimmed.contains(['/', '\\', ':', '*', '?', '"', '<', '>', '|']) {
-            return Err(VaultError::InvalidName(
-                "Vault name contains invalid characters".to_string(),
-            ));
-        }
-
-        Ok(())
+        NameValidator::normalize(NameKind::VaultName, name).map(|_| ())
     }
 
     /// Validate if vault can accept a new key
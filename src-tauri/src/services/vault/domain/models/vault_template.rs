@@ -0,0 +1,242 @@
+//! Vault template models
+//!
+//! Templates describe a recommended vault setup (protection policy, compression,
+//! exclusions, naming, freshness target and an inheritance checklist). The
+//! catalog itself is data - see `application/services/vault_templates.json`.
+
+use crate::services::shared::infrastructure::path_matches_any;
+use crate::services::vault::domain::models::{VaultItem, VaultItemCategory, VaultItemInput};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Recommended key setup for a vault
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+pub struct ProtectionPolicy {
+    /// Minimum number of passphrase keys
+    pub min_passphrase_keys: usize,
+    /// Minimum number of YubiKeys
+    pub min_yubikey_keys: usize,
+}
+
+/// Compression defaults for archives created from the vault
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+pub struct CompressionSettings {
+    /// gzip level (1-9, higher = smaller but slower)
+    pub level: u32,
+}
+
+/// A category of items the user should include (inheritance checklist)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+pub struct ChecklistCategory {
+    pub id: String,
+    pub label: String,
+    pub description: String,
+    /// Glob patterns that indicate an archived file belongs to this category
+    #[serde(default)]
+    pub match_patterns: Vec<String>,
+    /// Category of the vault item suggested for this entry
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub item_category: Option<VaultItemCategory>,
+}
+
+/// A built-in vault template
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+pub struct VaultTemplate {
+    pub id: String,
+    pub version: u32,
+    pub name: String,
+    pub description: String,
+    pub protection_policy: ProtectionPolicy,
+    pub compression: CompressionSettings,
+    /// Glob patterns for files that should not be archived
+    #[serde(default)]
+    pub exclusion_patterns: Vec<String>,
+    /// Archive name template (`{vault}` and `{date}` placeholders)
+    pub archive_name_template: String,
+    /// How often the vault should be re-encrypted to stay fresh
+    pub freshness_target_days: u32,
+    #[serde(default)]
+    pub checklist: Vec<ChecklistCategory>,
+}
+
+/// Template snapshot recorded in the vault manifest when a template is applied
+///
+/// The snapshot keeps the checklist and policy that were in effect at creation,
+/// so later catalog changes don't silently alter existing vaults.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AppliedTemplate {
+    pub id: String,
+    pub version: u32,
+    pub applied_at: DateTime<Utc>,
+    pub protection_policy: ProtectionPolicy,
+    pub compression: CompressionSettings,
+    #[serde(default)]
+    pub exclusion_patterns: Vec<String>,
+    pub archive_name_template: String,
+    pub freshness_target_days: u32,
+    #[serde(default)]
+    pub checklist: Vec<ChecklistCategory>,
+}
+
+/// Completion state of a single checklist category
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+pub struct ChecklistItemProgress {
+    pub id: String,
+    pub label: String,
+    pub completed: bool,
+    /// Archived files that satisfied this category
+    pub matched_files: Vec<String>,
+}
+
+/// Aggregated checklist completion
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+pub struct ChecklistProgress {
+    pub completed: usize,
+    pub total: usize,
+    pub items: Vec<ChecklistItemProgress>,
+}
+
+impl VaultTemplate {
+    /// Snapshot this template for recording in a vault manifest
+    pub fn to_applied(&self) -> AppliedTemplate {
+        AppliedTemplate {
+            id: self.id.clone(),
+            version: self.version,
+            applied_at: Utc::now(),
+            protection_policy: self.protection_policy.clone(),
+            compression: self.compression.clone(),
+            exclusion_patterns: self.exclusion_patterns.clone(),
+            archive_name_template: self.archive_name_template.clone(),
+            freshness_target_days: self.freshness_target_days,
+            checklist: self.checklist.clone(),
+        }
+    }
+}
+
+impl AppliedTemplate {
+    /// Incomplete vault items pre-populated from the checklist
+    pub fn suggested_items(&self, now: DateTime<Utc>) -> Vec<VaultItem> {
+        self.checklist
+            .iter()
+            .map(|category| {
+                VaultItem::new(
+                    VaultItemInput {
+                        category: category.item_category.unwrap_or(VaultItemCategory::Other),
+                        title: category.label.clone(),
+                        linked_paths: vec![],
+                        linked_archive_ids: vec![],
+                        note: None,
+                        completed: false,
+                    },
+                    now,
+                )
+            })
+            .collect()
+    }
+
+    /// Compute checklist completion against the archived file paths
+    ///
+    /// A category is complete when at least one archived file matches one of
+    /// its patterns. Categories without patterns can never be auto-completed.
+    pub fn checklist_progress<'a, I>(&self, archived_paths: I) -> ChecklistProgress
+    where
+        I: IntoIterator<Item = &'a str>,
+    {
+        let paths: Vec<&str> = archived_paths.into_iter().collect();
+
+        let items: Vec<ChecklistItemProgress> = self
+            .checklist
+            .iter()
+            .map(|category| {
+                let matched_files: Vec<String> = paths
+                    .iter()
+                    .filter(|p| path_matches_any(&category.match_patterns, p))
+                    .map(|p| p.to_string())
+                    .collect();
+
+                ChecklistItemProgress {
+                    id: category.id.clone(),
+                    label: category.label.clone(),
+                    completed: !matched_files.is_empty(),
+                    matched_files,
+                }
+            })
+            .collect();
+
+        ChecklistProgress {
+            completed: items.iter().filter(|i| i.completed).count(),
+            total: items.len(),
+            items,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_template() -> AppliedTemplate {
+        AppliedTemplate {
+            id: "test".to_string(),
+            version: 1,
+            applied_at: Utc::now(),
+            protection_policy: ProtectionPolicy {
+                min_passphrase_keys: 1,
+                min_yubikey_keys: 1,
+            },
+            compression: CompressionSettings { level: 6 },
+            exclusion_patterns: vec![],
+            archive_name_template: "{vault}".to_string(),
+            freshness_target_days: 90,
+            checklist: vec![
+                ChecklistCategory {
+                    id: "seed".to_string(),
+                    label: "Seed backup".to_string(),
+                    description: String::new(),
+                    match_patterns: vec!["*seed*".to_string()],
+                    item_category: Some(VaultItemCategory::SeedBackupLocation),
+                },
+                ChecklistCategory {
+                    id: "will".to_string(),
+                    label: "Will".to_string(),
+                    description: String::new(),
+                    match_patterns: vec!["*.pdf".to_string()],
+                    item_category: None,
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn test_checklist_progress() {
+        let template = test_template();
+        let progress = template.checklist_progress(["wallet/seed-words.txt", "photo.jpg"]);
+
+        assert_eq!(progress.total, 2);
+        assert_eq!(progress.completed, 1);
+        assert!(progress.items[0].completed);
+        assert_eq!(
+            progress.items[0].matched_files,
+            vec!["wallet/seed-words.txt"]
+        );
+        assert!(!progress.items[1].completed);
+    }
+
+    #[test]
+    fn test_checklist_progress_empty_vault() {
+        let progress = test_template().checklist_progress(std::iter::empty());
+        assert_eq!(progress.completed, 0);
+        assert_eq!(progress.total, 2);
+    }
+
+    #[test]
+    fn test_suggested_items_follow_checklist() {
+        let items = test_template().suggested_items(Utc::now());
+
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0].title, "Seed backup");
+        assert_eq!(items[0].category, VaultItemCategory::SeedBackupLocation);
+        assert_eq!(items[1].category, VaultItemCategory::Other);
+        assert!(items.iter().all(|item| !item.completed));
+    }
+}
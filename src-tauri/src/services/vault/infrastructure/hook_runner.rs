@@ -0,0 +1,301 @@
+//! Post-operation hook runner
+//!
+//! Starts a hook's program directly with its substituted arguments as argv;
+//! no shell is involved. The child gets a minimal environment rather than the
+//! app's, its stdin is closed, and it is killed once its timeout passes.
+//! Output is captured and truncated for the operation log.
+
+use crate::logging::in_current_trace;
+use crate::services::vault::domain::models::{
+    HookContext, HookDefinition, substitute_placeholders,
+};
+use serde::Serialize;
+use std::io::Read;
+use std::process::{Command, Stdio};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Output kept per hook run (stdout and stderr combined)
+pub const MAX_HOOK_OUTPUT_BYTES: usize = 2048;
+
+/// How often a running hook is checked for exit
+const POLL_INTERVAL: Duration = Duration::from_millis(25);
+
+/// Environment variables passed through from the app, when set
+const INHERITED_ENV: [&str; 4] = ["PATH", "HOME", "SystemRoot", "TMPDIR"];
+
+/// Result of one hook run
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, specta::Type)]
+pub struct HookRunOutcome {
+    pub hook_id: String,
+    /// Exit code; absent when the hook was killed or couldn't start
+    pub exit_code: Option<i32>,
+    pub timed_out: bool,
+    /// Why the program couldn't be started
+    pub error: Option<String>,
+    /// Start of the program's output
+    pub output: String,
+    pub output_truncated: bool,
+    pub duration_ms: u64,
+}
+
+impl HookRunOutcome {
+    pub fn succeeded(&self) -> bool {
+        self.exit_code == Some(0)
+    }
+
+    /// One-line description for the operation log
+    pub fn summary(&self, context: &HookContext) -> String {
+        let result = match (&self.error, self.exit_code) {
+            (Some(error), _) => format!("failed to start: {}", error),
+            _ if self.timed_out => "timed out and was killed".to_string(),
+            (None, Some(code)) => format!("exited with {}", code),
+            (None, None) => "was terminated".to_string(),
+        };
+        let mut summary = format!(
+            "Hook '{}' ({}) {} after {} ms",
+            self.hook_id,
+            context.event.as_str(),
+            result,
+            self.duration_ms
+        );
+        if !self.output.trim().is_empty() {
+            summary.push_str(": ");
+            summary.push_str(self.output.trim());
+            if self.output_truncated {
+                summary.push_str(" [truncated]");
+            }
+        }
+        summary
+    }
+}
+
+/// Run `hook` with `context`, waiting at most its timeout
+pub fn run_hook(hook: &HookDefinition, context: &HookContext) -> HookRunOutcome {
+    let started = Instant::now();
+    let failed = |message: String| HookRunOutcome {
+        hook_id: hook.id.clone(),
+        exit_code: None,
+        timed_out: false,
+        error: Some(message),
+        output: String::new(),
+        output_truncated: false,
+        duration_ms: started.elapsed().as_millis() as u64,
+    };
+
+    let args = match hook
+        .args
+        .iter()
+        .map(|arg| substitute_placeholders(arg, context))
+        .collect::<Result<Vec<_>, _>>()
+    {
+        Ok(args) => args,
+        Err(e) => return failed(e),
+    };
+
+    let mut command = Command::new(&hook.executable);
+    command
+        .args(&args)
+        .env_clear()
+        .env("BARQLY_HOOK_EVENT", context.event.as_str())
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    for key in INHERITED_ENV {
+        if let Some(value) = std::env::var_os(key) {
+            command.env(key, value);
+        }
+    }
+
+    let mut child = match command.spawn() {
+        Ok(child) => child,
+        Err(e) => return failed(e.to_string()),
+    };
+
+    // Drain both pipes so a chatty hook can't block on a full pipe
+    let (sender, receiver) = mpsc::channel();
+    let readers: Vec<_> = [
+        child
+            .stdout
+            .take()
+            .map(|s| Box::new(s) as Box<dyn Read + Send>),
+        child
+            .stderr
+            .take()
+            .map(|s| Box::new(s) as Box<dyn Read + Send>),
+    ]
+    .into_iter()
+    .flatten()
+    .map(|mut stream| {
+        let sender = sender.clone();
+        thread::spawn(in_current_trace(move || {
+            let mut buf = [0u8; 1024];
+            while let Ok(n) = stream.read(&mut buf) {
+                if n == 0 || sender.send(buf[..n].to_vec()).is_err() {
+                    break;
+                }
+            }
+        }))
+    })
+    .collect();
+    drop(sender);
+
+    let deadline = started + Duration::from_secs(u64::from(hook.timeout_secs));
+    let mut timed_out = false;
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break Some(status),
+            Ok(None) if Instant::now() >= deadline => {
+                timed_out = true;
+                let _ = child.kill();
+                break child.wait().ok();
+            }
+            Ok(None) => thread::sleep(POLL_INTERVAL),
+            Err(_) => {
+                let _ = child.kill();
+                break child.wait().ok();
+            }
+        }
+    };
+
+    // A grandchild may still hold the pipes open; don't wait on it
+    if timed_out {
+        drop(readers);
+    } else {
+        for reader in readers {
+            let _ = reader.join();
+        }
+    }
+    let mut captured = Vec::new();
+    let mut output_truncated = false;
+    for chunk in receiver.try_iter() {
+        let room = MAX_HOOK_OUTPUT_BYTES - captured.len();
+        output_truncated |= chunk.len() > room;
+        captured.extend_from_slice(&chunk[..chunk.len().min(room)]);
+    }
+
+    HookRunOutcome {
+        hook_id: hook.id.clone(),
+        exit_code: if timed_out {
+            None
+        } else {
+            status.and_then(|s| s.code())
+        },
+        timed_out,
+        error: None,
+        output: String::from_utf8_lossy(&captured).into_owned(),
+        output_truncated,
+        duration_ms: started.elapsed().as_millis() as u64,
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::services::vault::domain::models::HookEvent;
+    use std::os::unix::fs::PermissionsExt;
+    use std::path::Path;
+    use tempfile::TempDir;
+
+    fn script(dir: &Path, body: &str) -> String {
+        let path = dir.join("hook.sh");
+        std::fs::write(&path, format!("#!/bin/sh\n{}\n", body)).unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+        path.display().to_string()
+    }
+
+    fn hook(executable: String, args: &[&str], timeout_secs: u32) -> HookDefinition {
+        HookDefinition {
+            id: "test".to_string(),
+            event: HookEvent::EncryptionCompleted,
+            executable,
+            args: args.iter().map(|a| a.to_string()).collect(),
+            timeout_secs,
+            enabled: true,
+        }
+    }
+
+    fn context(vault_name: &str) -> HookContext {
+        HookContext {
+            event: HookEvent::EncryptionCompleted,
+            vault_id: "vault-001".to_string(),
+            vault_name: vault_name.to_string(),
+            archive_path: "/vaults/Family Docs.age".to_string(),
+            status: "success".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_arguments_reach_the_program_as_argv() {
+        let dir = TempDir::new().unwrap();
+        let marker = dir.path().join("pwned");
+        let executable = script(
+            dir.path(),
+            r#"for a in "$@"; do printf '<%s>\n' "$a"; done"#,
+        );
+        let name = format!(
+            "x; touch {} $(touch {}) `id`",
+            marker.display(),
+            marker.display()
+        );
+
+        let outcome = run_hook(
+            &hook(executable, &["{vault_name}", "--file={archive_path}"], 10),
+            &context(&name),
+        );
+
+        assert!(outcome.succeeded(), "{:?}", outcome);
+        assert_eq!(
+            outcome.output,
+            format!("<{}>\n<--file=/vaults/Family Docs.age>\n", name)
+        );
+        assert!(!marker.exists());
+    }
+
+    #[test]
+    fn test_environment_is_sanitized() {
+        unsafe {
+            std::env::set_var("BARQLY_HOOK_TEST_SECRET", "leak");
+        }
+        let dir = TempDir::new().unwrap();
+        let executable = script(
+            dir.path(),
+            r#"printf '%s|%s' "${BARQLY_HOOK_TEST_SECRET:-none}" "$BARQLY_HOOK_EVENT""#,
+        );
+
+        let outcome = run_hook(&hook(executable, &[], 10), &context("Family"));
+        assert_eq!(outcome.output, "none|encryption_completed");
+    }
+
+    #[test]
+    fn test_timeout_kills_the_hook() {
+        let dir = TempDir::new().unwrap();
+        let executable = script(dir.path(), "echo started\nexec sleep 30");
+
+        let outcome = run_hook(&hook(executable, &[], 1), &context("Family"));
+        assert!(outcome.timed_out);
+        assert_eq!(outcome.exit_code, None);
+        assert!(outcome.duration_ms < 10_000, "{:?}", outcome);
+        assert!(outcome.summary(&context("Family")).contains("killed"));
+    }
+
+    #[test]
+    fn test_exit_code_and_truncated_output() {
+        let dir = TempDir::new().unwrap();
+        let executable = script(dir.path(), "head -c 10000 /dev/zero | tr '\\0' x\nexit 3");
+
+        let outcome = run_hook(&hook(executable, &[], 10), &context("Family"));
+        assert_eq!(outcome.exit_code, Some(3));
+        assert!(outcome.output_truncated);
+        assert_eq!(outcome.output.len(), MAX_HOOK_OUTPUT_BYTES);
+
+        let missing = run_hook(
+            &hook("/nonexistent/hook".to_string(), &[], 10),
+            &context("F"),
+        );
+        assert_eq!(missing.exit_code, None);
+        assert!(missing.error.is_some());
+        assert!(missing.summary(&context("F")).contains("failed to start"));
+    }
+}
@@ -1,10 +1,13 @@
+pub mod hook_runner;
 pub mod persistence;
 pub mod vault_repository;
 
+pub use hook_runner::{HookRunOutcome, run_hook};
 pub use vault_repository::VaultRepository;
 
 // Re-export persistence functions for convenience
 pub use persistence::{
     MetadataStorage, RecipientInfo, RecipientType, VaultMetadata, delete_vault, get_current_vault,
-    get_vault, list_vaults, load_vault, save_vault, vault_exists,
+    get_vault, list_vaults, load_vault, save_vault, vault_exists, vault_exists_sync,
+    vault_pending_write,
 };
@@ -0,0 +1,246 @@
+//! App configuration
+//!
+//! Device-wide state that isn't tied to a vault: the app version seen on the
+//! last start, so an upgrade can be detected and the compatibility changes
+//! since then explained, and device-wide preferences such as storage quotas
+//! and how to name archives that clash with another vault's in a shared
+//! output folder, the locale display text is formatted in, and the ID that
+//! marks this profile's writes to vault files shared with other devices,
+//! how much an archive patch may replace, how long to wait for the
+//! interface to load before recording the launch as failed, and whether
+//! values may be copied to the clipboard and for how long.
+//! Stored as a single JSON file in the config directory.
+
+use crate::services::shared::infrastructure::formatting::Locale;
+use crate::services::shared::infrastructure::io::atomic_write_sync;
+use crate::services::shared::infrastructure::path_management::get_config_dir;
+use crate::services::vault::domain::models::{
+    AppVersion, ClipboardSettings, CrossVaultNamePolicy, DEFAULT_FRONTEND_READY_TIMEOUT,
+    DEFAULT_PATCH_SIZE_CAP, StorageQuotas,
+};
+use crate::types::{ByteSize, DurationMs, IoPriority};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tracing::{debug, warn};
+
+const CONFIG_FILENAME: &str = "app_config.json";
+const CONFIG_SCHEMA: &str = "barqly.vault.app-config/1";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AppConfig {
+    pub schema: String,
+    /// App version of the most recent successful start
+    #[serde(default)]
+    pub last_run_version: Option<String>,
+    /// Version that ran before `last_run_version`, kept so the changes since
+    /// the upgrade can still be listed after the start that recorded it
+    #[serde(default)]
+    pub previous_run_version: Option<String>,
+    /// I/O priority for long-running operations that don't choose one
+    #[serde(default)]
+    pub default_io_priority: IoPriority,
+    /// Size limits of the app's working data, enforced by storage cleanup
+    #[serde(default)]
+    pub storage_quotas: StorageQuotas,
+    /// Rename or refuse an archive whose name another vault already has in
+    /// the same output folder
+    #[serde(default)]
+    pub cross_vault_name_policy: CrossVaultNamePolicy,
+    /// Names this profile as the last writer of vault manifests and index
+    /// entries, generated on first use
+    #[serde(default)]
+    pub device_id: Option<String>,
+    /// Locale tag sizes and dates are displayed in, e.g. `de-AT`; English
+    /// when unset or unsupported
+    #[serde(default)]
+    pub locale: Option<String>,
+    /// Don't read archives back after writing them to removable media, for
+    /// archives too large to read twice; each encryption records a warning
+    #[serde(default)]
+    pub skip_removable_read_back: bool,
+    /// Most an archive patch may replace, in replacement files together
+    #[serde(default = "default_patch_size_cap")]
+    pub patch_size_cap: ByteSize,
+    /// Startup window for the interface's `frontend_ready` handshake
+    #[serde(default = "default_frontend_ready_timeout")]
+    pub frontend_ready_timeout: DurationMs,
+    /// Copying to the clipboard (off by default) and when copies are cleared
+    #[serde(default)]
+    pub clipboard: ClipboardSettings,
+}
+
+impl Default for AppConfig {
+    fn default() -> Self {
+        Self {
+            schema: CONFIG_SCHEMA.to_string(),
+            last_run_version: None,
+            previous_run_version: None,
+            default_io_priority: IoPriority::Normal,
+            storage_quotas: StorageQuotas::default(),
+            cross_vault_name_policy: CrossVaultNamePolicy::default(),
+            device_id: None,
+            locale: None,
+            skip_removable_read_back: false,
+            patch_size_cap: DEFAULT_PATCH_SIZE_CAP,
+            frontend_ready_timeout: DEFAULT_FRONTEND_READY_TIMEOUT,
+            clipboard: ClipboardSettings::default(),
+        }
+    }
+}
+
+fn default_patch_size_cap() -> ByteSize {
+    DEFAULT_PATCH_SIZE_CAP
+}
+
+fn default_frontend_ready_timeout() -> DurationMs {
+    DEFAULT_FRONTEND_READY_TIMEOUT
+}
+
+impl AppConfig {
+    pub(crate) fn get_config_path() -> Result<PathBuf, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(get_config_dir()?.join(CONFIG_FILENAME))
+    }
+
+    /// Load the config from the config directory (default if none saved yet)
+    pub fn load() -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        Self::load_from(&Self::get_config_path()?)
+    }
+
+    /// Save the config to the config directory
+    pub fn save(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.save_to(&Self::get_config_path()?)
+    }
+
+    pub fn load_from(path: &Path) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        if !path.exists() {
+            debug!("App config doesn't exist, using defaults");
+            return Ok(Self::default());
+        }
+
+        let content = fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&content)?)
+    }
+
+    pub fn save_to(&self, path: &Path) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let json = serde_json::to_string_pretty(self)?;
+        atomic_write_sync(path, json.as_bytes())?;
+        debug!(last_run_version = ?self.last_run_version, "Saved app config");
+        Ok(())
+    }
+
+    /// Record a start of `current`, returning the version upgraded from
+    ///
+    /// Returns `None` on a fresh install, on a repeat start of the same version
+    /// and after a downgrade.
+    pub fn record_run(&mut self, current: AppVersion) -> Option<AppVersion> {
+        let last = self.last_run_version.as_deref().and_then(AppVersion::parse);
+        if last == Some(current) {
+            return None;
+        }
+
+        self.previous_run_version = self.last_run_version.take();
+        self.last_run_version = Some(current.to_string());
+        last.filter(|last| *last < current)
+    }
+
+    /// This profile's device ID, generating one if none is set yet
+    ///
+    /// Returns whether it was generated, in which case the config needs saving.
+    pub fn ensure_device_id(&mut self) -> (String, bool) {
+        match &self.device_id {
+            Some(device_id) => (device_id.clone(), false),
+            None => {
+                let device_id = uuid::Uuid::new_v4().to_string();
+                self.device_id = Some(device_id.clone());
+                (device_id, true)
+            }
+        }
+    }
+
+    /// Locale to format display text in
+    pub fn display_locale(&self) -> Locale {
+        self.locale
+            .as_deref()
+            .map(Locale::from_tag)
+            .unwrap_or_default()
+    }
+
+    /// Version that ran before this one, if the app has been upgraded
+    pub fn upgraded_from(&self) -> Option<AppVersion> {
+        let last = self
+            .last_run_version
+            .as_deref()
+            .and_then(AppVersion::parse)?;
+        self.previous_run_version
+            .as_deref()
+            .and_then(AppVersion::parse)
+            .filter(|previous| *previous < last)
+    }
+}
+
+/// Configured display locale; English if the config can't be read
+pub fn configured_locale() -> Locale {
+    match AppConfig::load() {
+        Ok(config) => config.display_locale(),
+        Err(e) => {
+            warn!(error = %e, "Failed to load app config, formatting in English");
+            Locale::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_record_run_persists_last_version() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join(CONFIG_FILENAME);
+
+        let mut config = AppConfig::load_from(&path).unwrap();
+        assert_eq!(config.record_run(AppVersion::new(0, 2, 0)), None);
+        config.save_to(&path).unwrap();
+
+        let mut config = AppConfig::load_from(&path).unwrap();
+        assert_eq!(config.last_run_version.as_deref(), Some("0.2.0"));
+        assert_eq!(
+            config.record_run(AppVersion::new(0, 2, 2)),
+            Some(AppVersion::new(0, 2, 0))
+        );
+        config.save_to(&path).unwrap();
+
+        // A repeat start keeps the version upgraded from
+        let mut config = AppConfig::load_from(&path).unwrap();
+        assert_eq!(config.record_run(AppVersion::new(0, 2, 2)), None);
+        assert_eq!(config.last_run_version.as_deref(), Some("0.2.2"));
+        assert_eq!(config.upgraded_from(), Some(AppVersion::new(0, 2, 0)));
+    }
+
+    #[test]
+    fn test_downgrade_is_not_an_upgrade() {
+        let mut config = AppConfig {
+            last_run_version: Some("0.3.0".into()),
+            ..Default::default()
+        };
+        assert_eq!(config.record_run(AppVersion::new(0, 2, 2)), None);
+        assert_eq!(config.last_run_version.as_deref(), Some("0.2.2"));
+        assert_eq!(config.upgraded_from(), None);
+    }
+
+    #[test]
+    fn test_device_id_is_generated_once() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join(CONFIG_FILENAME);
+
+        let mut config = AppConfig::load_from(&path).unwrap();
+        let (device_id, generated) = config.ensure_device_id();
+        assert!(generated);
+        config.save_to(&path).unwrap();
+
+        let mut config = AppConfig::load_from(&path).unwrap();
+        assert_eq!(config.ensure_device_id(), (device_id, false));
+    }
+}
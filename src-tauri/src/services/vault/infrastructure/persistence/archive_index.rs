@@ -0,0 +1,476 @@
+//! Archive index
+//!
+//! Device-local record of every encryption (archive) per vault, with the
+//! optional comment entered at encryption time. Lets users search past
+//! archives without decrypting them. Stored as a single JSON file in the
+//! config directory, keyed by vault ID.
+//!
+//! With a shared vault store, the entries of the store's vaults live in the
+//! store's own `archive_index.json` instead, so every account sharing it sees
+//! them; `load` and `save` merge and split the two.
+//!
+//! Patches replacing a few files of an archive are kept apart from the
+//! archives themselves, per vault, so nothing that weighs archives against
+//! each other (retention, pruning, conflicts) mistakes one for an encryption.
+//!
+//! Each vault's entries carry a generation stamp, bumped by `save` whenever
+//! they change, so a copy synced in from another device can't silently
+//! replace this device's entries (see `vault_generation`).
+
+use crate::services::shared::infrastructure::io::atomic_write_sync;
+use crate::services::shared::infrastructure::path_management::{
+    STORE_MANIFESTS_DIR, get_config_dir, get_shared_store_dir,
+};
+use crate::services::vault::domain::models::{
+    ArchiveIndexEntry, ArchivePatchEntry, ConflictEvidence, GenerationFile, GenerationStamp,
+};
+use crate::services::vault::infrastructure::persistence::vault_generation::{
+    GenerationLedger, VaultConflictError, conflicted_copies,
+};
+use crate::services::vault::infrastructure::persistence::vault_persistence::manifests_in;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tracing::debug;
+
+const INDEX_FILENAME: &str = "archive_index.json";
+const INDEX_SCHEMA: &str = "barqly.vault.archive-index/1";
+
+/// All archives recorded on this device
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArchiveIndex {
+    pub schema: String,
+    /// Oldest first per vault
+    #[serde(default)]
+    pub vaults: HashMap<String, Vec<ArchiveIndexEntry>>,
+    /// Generation of each vault's entries and the device that wrote them
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub generations: HashMap<String, GenerationStamp>,
+    /// Patch layers per vault, oldest first
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub patches: HashMap<String, Vec<ArchivePatchEntry>>,
+}
+
+impl Default for ArchiveIndex {
+    fn default() -> Self {
+        Self {
+            schema: INDEX_SCHEMA.to_string(),
+            vaults: HashMap::new(),
+            generations: HashMap::new(),
+            patches: HashMap::new(),
+        }
+    }
+}
+
+impl ArchiveIndex {
+    pub fn get_index_path() -> Result<PathBuf, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(get_config_dir()?.join(INDEX_FILENAME))
+    }
+
+    /// Load the index from the config directory (empty if none saved yet),
+    /// with the shared store's entries if this profile uses one
+    pub fn load() -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        let mut index = Self::load_from(&Self::get_index_path()?)?;
+        if let Some(store_dir) = get_shared_store_dir()? {
+            index.merge(Self::load_from(&Self::store_index_path(&store_dir))?);
+        }
+        Ok(index)
+    }
+
+    /// Save the index to the config directory, and the entries of shared
+    /// vaults to the shared store
+    ///
+    /// Each file is only rewritten when its part changed, so saving this
+    /// profile's own entries never waits on the store lock. Fails with
+    /// `VaultConflictError` if a changed vault is suspected to be in conflict.
+    pub fn save(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let ledger = GenerationLedger::load()?;
+        let Some(store_dir) = get_shared_store_dir()? else {
+            return self.save_stamped(&Self::get_index_path()?, &ledger);
+        };
+
+        let shared_ids: HashSet<String> = manifests_in(&store_dir.join(STORE_MANIFESTS_DIR))
+            .iter()
+            .map(|metadata| metadata.vault_id().to_string())
+            .collect();
+        let (shared, own) = self.split(|vault_id| shared_ids.contains(vault_id));
+        shared.save_stamped(&Self::store_index_path(&store_dir), &ledger)?;
+        own.save_stamped(&Self::get_index_path()?, &ledger)
+    }
+
+    /// Index of the shared vault store at `store_dir`
+    pub fn store_index_path(store_dir: &Path) -> PathBuf {
+        store_dir.join(INDEX_FILENAME)
+    }
+
+    /// Write to `path`, stamping the vaults whose entries differ from the
+    /// file's with `ledger`'s next generation
+    ///
+    /// Unchanged vaults keep the file's stamps, and an unchanged file isn't
+    /// rewritten.
+    pub fn save_stamped(
+        &self,
+        path: &Path,
+        ledger: &GenerationLedger,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let on_disk = Self::load_from(path)?;
+        let changed: Vec<&String> = self
+            .vaults
+            .keys()
+            .chain(on_disk.vaults.keys())
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .filter(|vault_id| {
+                self.entries(vault_id) != on_disk.entries(vault_id)
+                    || self.patches(vault_id) != on_disk.patches(vault_id)
+            })
+            .collect();
+
+        let mut stamped = Self {
+            generations: on_disk.generations.clone(),
+            ..self.clone()
+        };
+        for vault_id in &changed {
+            if let Some(copy) = diverging_copy(path, &on_disk, vault_id) {
+                return Err(Box::new(VaultConflictError {
+                    vault_id: vault_id.to_string(),
+                    evidence: ConflictEvidence::ConflictedCopy {
+                        file: GenerationFile::ArchiveIndex,
+                        path: copy.display().to_string(),
+                    },
+                }));
+            }
+            let stamp = ledger.next_stamp(
+                vault_id,
+                GenerationFile::ArchiveIndex,
+                &on_disk.generation(vault_id),
+            )?;
+            stamped.generations.insert(vault_id.to_string(), stamp);
+        }
+
+        if stamped == on_disk {
+            return Ok(());
+        }
+        stamped.save_to(path)?;
+        for vault_id in changed {
+            ledger.record(
+                vault_id,
+                GenerationFile::ArchiveIndex,
+                stamped.generation(vault_id).generation,
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Add another index's vaults, replacing any this one already has
+    pub fn merge(&mut self, other: Self) {
+        self.vaults.extend(other.vaults);
+        self.generations.extend(other.generations);
+        self.patches.extend(other.patches);
+    }
+
+    /// Split into the vaults `is_shared` picks and the rest
+    pub fn split(&self, is_shared: impl Fn(&str) -> bool) -> (Self, Self) {
+        let (shared, own) = self
+            .vaults
+            .iter()
+            .map(|(vault_id, entries)| (vault_id.clone(), entries.clone()))
+            .partition(|(vault_id, _)| is_shared(vault_id));
+        let (shared_generations, own_generations) = self
+            .generations
+            .iter()
+            .map(|(vault_id, stamp)| (vault_id.clone(), stamp.clone()))
+            .partition(|(vault_id, _)| is_shared(vault_id));
+        let (shared_patches, own_patches) = self
+            .patches
+            .iter()
+            .map(|(vault_id, patches)| (vault_id.clone(), patches.clone()))
+            .partition(|(vault_id, _)| is_shared(vault_id));
+        (
+            Self {
+                vaults: shared,
+                generations: shared_generations,
+                patches: shared_patches,
+                ..Default::default()
+            },
+            Self {
+                vaults: own,
+                generations: own_generations,
+                patches: own_patches,
+                ..Default::default()
+            },
+        )
+    }
+
+    /// Generation stamp of a vault's entries (default if never stamped)
+    pub fn generation(&self, vault_id: &str) -> GenerationStamp {
+        self.generations.get(vault_id).cloned().unwrap_or_default()
+    }
+
+    /// Whether this index and `other` disagree about a vault
+    ///
+    /// An index that has neither entries nor a stamp for the vault says
+    /// nothing about it.
+    pub fn diverges_from(&self, other: &Self, vault_id: &str) -> bool {
+        let knows = |index: &Self| {
+            index.vaults.contains_key(vault_id) || index.generations.contains_key(vault_id)
+        };
+        knows(self)
+            && (self.generation(vault_id) != other.generation(vault_id)
+                || self.entries(vault_id) != other.entries(vault_id)
+                || self.patches(vault_id) != other.patches(vault_id))
+    }
+
+    pub fn load_from(path: &Path) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        if !path.exists() {
+            debug!("Archive index doesn't exist, starting empty");
+            return Ok(Self::default());
+        }
+
+        let content = fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&content)?)
+    }
+
+    pub fn save_to(&self, path: &Path) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let json = serde_json::to_string_pretty(self)?;
+        atomic_write_sync(path, json.as_bytes())?;
+        debug!(vault_count = self.vaults.len(), "Saved archive index");
+        Ok(())
+    }
+
+    /// Archives recorded for a vault, oldest first
+    pub fn entries(&self, vault_id: &str) -> &[ArchiveIndexEntry] {
+        self.vaults.get(vault_id).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    /// Record a new archive
+    pub fn record(&mut self, entry: ArchiveIndexEntry) {
+        self.vaults
+            .entry(entry.vault_id.clone())
+            .or_default()
+            .push(entry);
+    }
+
+    /// Latest entry written to `archive_name` in any vault; the archive file
+    /// on disk holds this encryption
+    pub fn current_entry(&self, archive_name: &str) -> Option<&ArchiveIndexEntry> {
+        self.entries_named(archive_name)
+            .max_by_key(|entry| entry.created_at)
+    }
+
+    /// Entries written to `archive_name` in any vault
+    pub fn entries_named<'a>(
+        &'a self,
+        archive_name: &'a str,
+    ) -> impl Iterator<Item = &'a ArchiveIndexEntry> + 'a {
+        self.vaults
+            .values()
+            .flatten()
+            .filter(move |entry| entry.archive_name == archive_name)
+    }
+
+    /// Remove an entry, e.g. when its archive turned out to be incomplete
+    pub fn remove(&mut self, vault_id: &str, archive_id: &str) -> Option<ArchiveIndexEntry> {
+        let entries = self.vaults.get_mut(vault_id)?;
+        let position = entries
+            .iter()
+            .position(|entry| entry.archive_id == archive_id)?;
+        Some(entries.remove(position))
+    }
+
+    /// Mutable entry by archive ID
+    pub fn find_mut(&mut self, vault_id: &str, archive_id: &str) -> Option<&mut ArchiveIndexEntry> {
+        self.vaults
+            .get_mut(vault_id)?
+            .iter_mut()
+            .find(|entry| entry.archive_id == archive_id)
+    }
+
+    /// Patches recorded for a vault, oldest first
+    pub fn patches(&self, vault_id: &str) -> &[ArchivePatchEntry] {
+        self.patches.get(vault_id).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    /// Patches layered on one archive, oldest first
+    pub fn patches_of<'a>(
+        &'a self,
+        vault_id: &str,
+        base_archive_id: &'a str,
+    ) -> impl Iterator<Item = &'a ArchivePatchEntry> + 'a {
+        self.patches(vault_id)
+            .iter()
+            .filter(move |patch| patch.manifest.base_archive_id == base_archive_id)
+    }
+
+    /// Record a new patch
+    pub fn record_patch(&mut self, patch: ArchivePatchEntry) {
+        self.patches
+            .entry(patch.manifest.vault_id.clone())
+            .or_default()
+            .push(patch);
+    }
+
+    /// Remove the patches layered on an archive, e.g. when it's pruned
+    pub fn remove_patches_of(
+        &mut self,
+        vault_id: &str,
+        base_archive_id: &str,
+    ) -> Vec<ArchivePatchEntry> {
+        let Some(patches) = self.patches.get_mut(vault_id) else {
+            return Vec::new();
+        };
+        let (removed, kept) = std::mem::take(patches)
+            .into_iter()
+            .partition(|patch| patch.manifest.base_archive_id == base_archive_id);
+        *patches = kept;
+        removed
+    }
+}
+
+/// A sync service's conflicting copy of the index at `path` that disagrees
+/// with `on_disk` about a vault
+fn diverging_copy(path: &Path, on_disk: &ArchiveIndex, vault_id: &str) -> Option<PathBuf> {
+    conflicted_copies(path).into_iter().find(|copy| {
+        ArchiveIndex::load_from(copy).is_ok_and(|index| index.diverges_from(on_disk, vault_id))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::vault::domain::models::{PATCH_MANIFEST_SCHEMA, PatchManifest};
+    use chrono::Utc;
+    use tempfile::TempDir;
+
+    fn entry(archive_id: &str, comment: Option<&str>) -> ArchiveIndexEntry {
+        ArchiveIndexEntry {
+            archive_id: archive_id.to_string(),
+            vault_id: "vault-001".to_string(),
+            archive_name: "Family.age".to_string(),
+            encryption_revision: 1,
+            created_at: Utc::now(),
+            file_count: 3,
+            comment: comment.map(str::to_string),
+            comment_updated_at: None,
+            immutable: false,
+            parity: None,
+            migrated_from: None,
+            prune_marked: false,
+            remapped_from: None,
+            archive_sha256: None,
+            recipients: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_missing_file_loads_empty() {
+        let temp = TempDir::new().unwrap();
+        let index = ArchiveIndex::load_from(&temp.path().join("none.json")).unwrap();
+
+        assert!(index.vaults.is_empty());
+        assert!(index.entries("vault-001").is_empty());
+    }
+
+    #[test]
+    fn test_round_trip_and_find() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join(INDEX_FILENAME);
+
+        let mut index = ArchiveIndex::default();
+        index.record(entry("a1", Some("before the move")));
+        index.record(entry("a2", None));
+        index.save_to(&path).unwrap();
+
+        let mut loaded = ArchiveIndex::load_from(&path).unwrap();
+        assert_eq!(loaded, index);
+        assert_eq!(loaded.entries("vault-001").len(), 2);
+
+        let found = loaded.find_mut("vault-001", "a2").unwrap();
+        assert_eq!(found.comment, None);
+        assert!(loaded.find_mut("vault-001", "missing").is_none());
+        assert!(loaded.find_mut("vault-002", "a1").is_none());
+    }
+
+    #[test]
+    fn test_current_entry_is_latest_for_name() {
+        let mut index = ArchiveIndex::default();
+        let mut older = entry("a1", None);
+        older.created_at -= chrono::Duration::days(1);
+        index.record(older);
+        index.record(entry("a2", None));
+
+        assert_eq!(index.current_entry("Family.age").unwrap().archive_id, "a2");
+        assert!(index.current_entry("Other.age").is_none());
+    }
+
+    fn patch(patch_id: &str, base_archive_id: &str) -> ArchivePatchEntry {
+        ArchivePatchEntry {
+            patch_name: format!("Family.patch-1-{}.age", patch_id),
+            archive_sha256: "00".repeat(32),
+            manifest: PatchManifest {
+                schema: PATCH_MANIFEST_SCHEMA.to_string(),
+                patch_id: patch_id.to_string(),
+                vault_id: "vault-001".to_string(),
+                base_archive_id: base_archive_id.to_string(),
+                base_revision: 1,
+                sequence: 1,
+                created_at: Utc::now(),
+                entries: Vec::new(),
+            },
+        }
+    }
+
+    #[test]
+    fn test_patches_are_kept_per_base_archive() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join(INDEX_FILENAME);
+        let mut index = ArchiveIndex::default();
+        index.record(entry("a1", None));
+        index.record(entry("a2", None));
+        index.record_patch(patch("p1", "a1"));
+        index.record_patch(patch("p2", "a2"));
+        index.save_to(&path).unwrap();
+
+        let mut loaded = ArchiveIndex::load_from(&path).unwrap();
+        assert_eq!(loaded, index);
+        assert_eq!(loaded.patches_of("vault-001", "a1").count(), 1);
+
+        let removed = loaded.remove_patches_of("vault-001", "a1");
+        assert_eq!(removed[0].manifest.patch_id, "p1");
+        assert_eq!(loaded.patches("vault-001").len(), 1);
+        assert_eq!(loaded.patches_of("vault-001", "a1").count(), 0);
+        // Patches don't count as archives
+        assert_eq!(loaded.entries("vault-001").len(), 2);
+    }
+
+    #[test]
+    fn test_profiles_share_the_store_part() {
+        let temp = TempDir::new().unwrap();
+        let store_path = temp.path().join("store.json");
+        let bob_path = temp.path().join("bob.json");
+
+        // Alice records an archive of the shared vault and one of her own
+        let mut alice = ArchiveIndex::default();
+        alice.record(entry("a1", None));
+        let mut own = entry("a2", None);
+        own.vault_id = "vault-alice".to_string();
+        alice.record(own);
+        let (shared, alice_own) = alice.split(|vault_id| vault_id == "vault-001");
+        shared.save_to(&store_path).unwrap();
+        assert_eq!(alice_own.entries("vault-alice").len(), 1);
+        assert!(alice_own.entries("vault-001").is_empty());
+
+        // Bob sees the shared vault's archive next to his own, not Alice's
+        let mut bob = ArchiveIndex::default();
+        let mut bobs = entry("b1", None);
+        bobs.vault_id = "vault-bob".to_string();
+        bob.record(bobs);
+        bob.save_to(&bob_path).unwrap();
+        let mut loaded = ArchiveIndex::load_from(&bob_path).unwrap();
+        loaded.merge(ArchiveIndex::load_from(&store_path).unwrap());
+        assert_eq!(loaded.entries("vault-001")[0].archive_id, "a1");
+        assert_eq!(loaded.entries("vault-bob").len(), 1);
+        assert!(loaded.entries("vault-alice").is_empty());
+    }
+}
@@ -0,0 +1,192 @@
+//! File search index
+//!
+//! Device-local copy of every archive's file list, keyed by vault ID, so
+//! files can be found across archives without decrypting them. Stored as a
+//! single JSON file in the app data directory. It only caches what the
+//! archive index and manifests already hold, so a missing, unreadable, or
+//! outdated file is rebuilt rather than reported.
+
+use crate::services::shared::infrastructure::io::atomic_write_sync;
+use crate::services::shared::infrastructure::path_management::get_app_dir;
+use crate::services::vault::domain::models::IndexedArchive;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tracing::debug;
+
+const INDEX_FILENAME: &str = "file_search_index.json";
+const INDEX_SCHEMA: &str = "barqly.vault.file-search-index/1";
+
+/// Archive file lists recorded on this device
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileSearchIndex {
+    pub schema: String,
+    #[serde(default)]
+    pub vaults: HashMap<String, Vec<IndexedArchive>>,
+}
+
+impl Default for FileSearchIndex {
+    fn default() -> Self {
+        Self {
+            schema: INDEX_SCHEMA.to_string(),
+            vaults: HashMap::new(),
+        }
+    }
+}
+
+impl FileSearchIndex {
+    pub fn get_index_path() -> Result<PathBuf, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(get_app_dir()?.join(INDEX_FILENAME))
+    }
+
+    /// Load the index, or `None` when it is missing, unreadable, or from
+    /// another schema and needs rebuilding
+    pub fn load_from(path: &Path) -> Option<Self> {
+        let content = match fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) => {
+                debug!(error = %e, "File search index not readable");
+                return None;
+            }
+        };
+
+        match serde_json::from_str::<Self>(&content) {
+            Ok(index) if index.schema == INDEX_SCHEMA => Some(index),
+            Ok(index) => {
+                debug!(schema = %index.schema, "File search index has an old schema");
+                None
+            }
+            Err(e) => {
+                debug!(error = %e, "File search index is corrupt");
+                None
+            }
+        }
+    }
+
+    pub fn save_to(&self, path: &Path) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let json = serde_json::to_string(self)?;
+        atomic_write_sync(path, json.as_bytes())?;
+        debug!(vault_count = self.vaults.len(), "Saved file search index");
+        Ok(())
+    }
+
+    /// Every indexed archive
+    pub fn archives(&self) -> impl Iterator<Item = &IndexedArchive> {
+        self.vaults.values().flatten()
+    }
+
+    /// Add an archive, replacing an earlier copy with the same ID
+    pub fn upsert(&mut self, archive: IndexedArchive) {
+        let archives = self.vaults.entry(archive.vault_id.clone()).or_default();
+        archives.retain(|existing| existing.archive_id != archive.archive_id);
+        archives.push(archive);
+    }
+
+    /// Drop archives by ID, returning how many were indexed
+    pub fn remove_archives(&mut self, vault_id: &str, archive_ids: &[String]) -> usize {
+        let Some(archives) = self.vaults.get_mut(vault_id) else {
+            return 0;
+        };
+        let before = archives.len();
+        archives.retain(|archive| !archive_ids.contains(&archive.archive_id));
+        before - archives.len()
+    }
+
+    /// Drop everything indexed for a vault
+    pub fn remove_vault(&mut self, vault_id: &str) -> bool {
+        self.vaults.remove(vault_id).is_some()
+    }
+
+    /// Replace an archive's comment, returning whether it was indexed
+    pub fn set_comment(
+        &mut self,
+        vault_id: &str,
+        archive_id: &str,
+        comment: Option<String>,
+    ) -> bool {
+        let archive = self
+            .vaults
+            .get_mut(vault_id)
+            .and_then(|archives| archives.iter_mut().find(|a| a.archive_id == archive_id));
+        match archive {
+            Some(archive) => {
+                archive.comment = comment;
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::vault::domain::models::IndexedFile;
+    use chrono::Utc;
+    use tempfile::TempDir;
+
+    fn archive(archive_id: &str) -> IndexedArchive {
+        IndexedArchive {
+            vault_id: "vault-001".to_string(),
+            vault_name: "Family".to_string(),
+            archive_id: archive_id.to_string(),
+            archive_name: "Family.age".to_string(),
+            created_at: Utc::now(),
+            comment: None,
+            files: vec![IndexedFile {
+                path: "deed.pdf".to_string(),
+                size: 10,
+                content_type: Some("application/pdf".to_string()),
+            }],
+        }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join(INDEX_FILENAME);
+
+        let mut index = FileSearchIndex::default();
+        index.upsert(archive("a1"));
+        index.upsert(archive("a1"));
+        index.upsert(archive("a2"));
+        index.save_to(&path).unwrap();
+
+        let loaded = FileSearchIndex::load_from(&path).unwrap();
+        assert_eq!(loaded, index);
+        assert_eq!(loaded.archives().count(), 2);
+    }
+
+    #[test]
+    fn test_missing_corrupt_or_old_index_needs_rebuild() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join(INDEX_FILENAME);
+        assert!(FileSearchIndex::load_from(&path).is_none());
+
+        fs::write(&path, b"{\"schema\": \"barqly.vault.file-search").unwrap();
+        assert!(FileSearchIndex::load_from(&path).is_none());
+
+        fs::write(&path, br#"{"schema": "barqly.vault.file-search-index/0"}"#).unwrap();
+        assert!(FileSearchIndex::load_from(&path).is_none());
+    }
+
+    #[test]
+    fn test_remove_and_comment() {
+        let mut index = FileSearchIndex::default();
+        index.upsert(archive("a1"));
+        index.upsert(archive("a2"));
+
+        assert!(index.set_comment("vault-001", "a2", Some("deeds".to_string())));
+        assert!(!index.set_comment("vault-001", "missing", None));
+        assert_eq!(index.remove_archives("vault-001", &["a1".to_string()]), 1);
+        assert_eq!(index.remove_archives("vault-002", &["a2".to_string()]), 0);
+
+        let remaining: Vec<_> = index.archives().collect();
+        assert_eq!(remaining.len(), 1);
+        assert_eq!(remaining[0].comment.as_deref(), Some("deeds"));
+
+        assert!(index.remove_vault("vault-001"));
+        assert_eq!(index.archives().count(), 0);
+    }
+}
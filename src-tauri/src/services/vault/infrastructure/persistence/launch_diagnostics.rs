@@ -0,0 +1,69 @@
+//! Launch diagnostics
+//!
+//! Keeps the diagnostic record of the latest launch whose interface didn't
+//! load within the startup window, until the user exports or dismisses it.
+//! Stored as a single JSON file in the config directory.
+
+use crate::services::shared::infrastructure::io::atomic_write_sync;
+use crate::services::shared::infrastructure::path_management::get_config_dir;
+use crate::services::vault::domain::models::LaunchDiagnostics;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tracing::debug;
+
+const DIAGNOSTICS_FILENAME: &str = "launch_diagnostics.json";
+const STORE_SCHEMA: &str = "barqly.vault.launch-diagnostics-store/1";
+
+/// The latest failed launch on this device
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LaunchDiagnosticsStore {
+    pub schema: String,
+    #[serde(default)]
+    pub failed_launch: Option<LaunchDiagnostics>,
+}
+
+impl Default for LaunchDiagnosticsStore {
+    fn default() -> Self {
+        Self {
+            schema: STORE_SCHEMA.to_string(),
+            failed_launch: None,
+        }
+    }
+}
+
+impl LaunchDiagnosticsStore {
+    pub fn get_store_path() -> Result<PathBuf, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(get_config_dir()?.join(DIAGNOSTICS_FILENAME))
+    }
+
+    /// Load the store from the config directory (empty if none saved yet)
+    pub fn load() -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        Self::load_from(&Self::get_store_path()?)
+    }
+
+    /// Save the store to the config directory
+    pub fn save(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.save_to(&Self::get_store_path()?)
+    }
+
+    pub fn load_from(path: &Path) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        if !path.exists() {
+            debug!("Launch diagnostics don't exist, starting empty");
+            return Ok(Self::default());
+        }
+
+        let content = fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&content)?)
+    }
+
+    pub fn save_to(&self, path: &Path) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let json = serde_json::to_string_pretty(self)?;
+        atomic_write_sync(path, json.as_bytes())?;
+        debug!(
+            failed_launch = self.failed_launch.is_some(),
+            "Saved launch diagnostics"
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,111 @@
+//! Maintenance history
+//!
+//! Keeps the report from the most recent bulk maintenance run so it can be
+//! shown again after the run finishes. Stored as a single JSON file in the
+//! config directory.
+
+use crate::services::shared::infrastructure::io::atomic_write_sync;
+use crate::services::shared::infrastructure::path_management::get_config_dir;
+use crate::services::vault::domain::models::MaintenanceReport;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tracing::debug;
+
+const HISTORY_FILENAME: &str = "maintenance_report.json";
+const HISTORY_SCHEMA: &str = "barqly.vault.maintenance-history/1";
+
+/// Last maintenance report recorded on this device
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MaintenanceHistory {
+    pub schema: String,
+    #[serde(default)]
+    pub last_report: Option<MaintenanceReport>,
+}
+
+impl Default for MaintenanceHistory {
+    fn default() -> Self {
+        Self {
+            schema: HISTORY_SCHEMA.to_string(),
+            last_report: None,
+        }
+    }
+}
+
+impl MaintenanceHistory {
+    fn get_history_path() -> Result<PathBuf, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(get_config_dir()?.join(HISTORY_FILENAME))
+    }
+
+    /// Load the history from the config directory (empty if none saved yet)
+    pub fn load() -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        Self::load_from(&Self::get_history_path()?)
+    }
+
+    /// Save the history to the config directory
+    pub fn save(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.save_to(&Self::get_history_path()?)
+    }
+
+    pub fn load_from(path: &Path) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        if !path.exists() {
+            debug!("Maintenance history doesn't exist, starting empty");
+            return Ok(Self::default());
+        }
+
+        let content = fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&content)?)
+    }
+
+    pub fn save_to(&self, path: &Path) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let json = serde_json::to_string_pretty(self)?;
+        atomic_write_sync(path, json.as_bytes())?;
+        debug!("Saved maintenance history");
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::vault::domain::models::{
+        MaintenanceCell, MaintenanceFindings, MaintenanceTask,
+    };
+    use crate::types::DurationMs;
+    use chrono::Utc;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_report_round_trip() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join(HISTORY_FILENAME);
+
+        let empty = MaintenanceHistory::load_from(&path).unwrap();
+        assert!(empty.last_report.is_none());
+
+        let cell = MaintenanceCell::from_result(
+            "vault-001",
+            "Family",
+            MaintenanceTask::IntegrityVerification,
+            DurationMs(12),
+            Ok(MaintenanceFindings::default()),
+        );
+        let report = MaintenanceReport::new(
+            "maintenance_1",
+            Utc::now(),
+            vec![MaintenanceTask::IntegrityVerification],
+            vec!["vault-001".to_string()],
+            vec![cell],
+        );
+
+        let history = MaintenanceHistory {
+            last_report: Some(report.clone()),
+            ..Default::default()
+        };
+        history.save_to(&path).unwrap();
+
+        let loaded = MaintenanceHistory::load_from(&path).unwrap();
+        assert_eq!(loaded.schema, HISTORY_SCHEMA);
+        assert_eq!(loaded.last_report, Some(report));
+    }
+}
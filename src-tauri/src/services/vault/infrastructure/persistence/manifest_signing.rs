@@ -0,0 +1,504 @@
+//! Detached signatures for external vault manifests
+//!
+//! The external manifest in non-sync storage is plaintext JSON; anyone with
+//! write access could change its expected hashes so a tampered archive still
+//! verifies. Each vault therefore gets an Ed25519 signing key, kept next to
+//! the key files in the keys directory. Every write of the sidecar signs its
+//! canonical bytes (sorted keys, no whitespace, `signature` removed) and
+//! stores the signature with the signer's fingerprint. The fingerprint is also
+//! recorded in the manifest itself, so the copy embedded in an archive names
+//! the key its sidecar was signed with.
+//!
+//! Signing needs the vault's private signing key. Verifying needs only the
+//! public keys in the local signer registry, never a passphrase.
+
+use crate::error::StorageError;
+use crate::services::shared::infrastructure::{atomic_write_sync, get_keys_dir};
+use crate::services::vault::infrastructure::persistence::metadata::VaultMetadata;
+use base64::Engine as _;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use chrono::{DateTime, Utc};
+use ed25519_dalek::{Signature, Signer, SigningKey, VerifyingKey};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tracing::{debug, info, warn};
+use zeroize::Zeroizing;
+
+/// Top-level manifest field holding the detached signature
+const SIGNATURE_FIELD: &str = "signature";
+const SIGNATURE_ALGORITHM: &str = "ed25519";
+const SIGNING_KEYS_DIRNAME: &str = "manifest-signing";
+const SIGNERS_FILENAME: &str = "manifest_signers.json";
+
+/// Detached signature stored in an external manifest
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ManifestSignature {
+    pub algorithm: String,
+    /// Fingerprint of the signing public key
+    pub signer: String,
+    /// Base64 Ed25519 signature over the canonical manifest bytes
+    pub value: String,
+}
+
+/// Outcome of checking an external manifest's signature
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, specta::Type)]
+#[serde(rename_all = "snake_case")]
+pub enum SignatureStatus {
+    /// Legacy manifest written before signing; accepted with a notice
+    Unsigned,
+    ValidSignature,
+    /// Content changed after signing, or the signature is malformed
+    InvalidSignature,
+    /// Signed by a key this device doesn't know
+    UnknownSigner,
+}
+
+/// Signature status of an external manifest, with the signer it names
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, specta::Type)]
+pub struct ManifestSignatureCheck {
+    pub status: SignatureStatus,
+    pub signer: Option<String>,
+}
+
+impl ManifestSignatureCheck {
+    fn new(status: SignatureStatus, signer: Option<&str>) -> Self {
+        Self {
+            status,
+            signer: signer.map(str::to_string),
+        }
+    }
+
+    /// True unless the manifest may have been tampered with
+    pub fn is_acceptable(&self) -> bool {
+        matches!(
+            self.status,
+            SignatureStatus::Unsigned | SignatureStatus::ValidSignature
+        )
+    }
+
+    /// Short explanation for anything but a valid signature
+    pub fn notice(&self) -> Option<&'static str> {
+        match self.status {
+            SignatureStatus::ValidSignature => None,
+            SignatureStatus::Unsigned => Some("Manifest is unsigned (written by an older version)"),
+            SignatureStatus::InvalidSignature => Some("Manifest was modified after it was signed"),
+            SignatureStatus::UnknownSigner => Some("Manifest was signed by an unknown key"),
+        }
+    }
+}
+
+/// Public signing key of a vault, as known to this device
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct KnownSigner {
+    fingerprint: String,
+    vault_id: String,
+    /// Hex-encoded Ed25519 public key
+    public_key: String,
+    created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct SignerRegistry {
+    #[serde(default)]
+    signers: Vec<KnownSigner>,
+}
+
+/// Signs and verifies external manifests with per-vault keys
+#[derive(Debug, Clone)]
+pub struct ManifestSigner {
+    keys_dir: PathBuf,
+}
+
+impl ManifestSigner {
+    /// Signer keeping its keys under `keys_dir`
+    pub fn new(keys_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            keys_dir: keys_dir.into(),
+        }
+    }
+
+    /// Signer using the app's keys directory
+    pub fn from_keys_dir() -> Result<Self, StorageError> {
+        Ok(Self::new(get_keys_dir()?))
+    }
+
+    /// Fingerprint of a vault's signing key, creating the key on first use
+    pub fn fingerprint_for(&self, vault_id: &str) -> Result<String, StorageError> {
+        Ok(fingerprint(&self.signing_key(vault_id)?.verifying_key()))
+    }
+
+    /// Copy of `manifest` with the signer recorded and a fresh signature
+    pub fn sign(&self, manifest: &VaultMetadata) -> Result<VaultMetadata, StorageError> {
+        let key = self.signing_key(manifest.vault_id())?;
+        let mut signed = manifest.clone();
+        signed.signer_fingerprint = Some(fingerprint(&key.verifying_key()));
+        signed.signature = None;
+
+        let bytes = canonical_bytes(&serde_json::to_value(&signed)?);
+        signed.signature = Some(ManifestSignature {
+            algorithm: SIGNATURE_ALGORITHM.to_string(),
+            signer: fingerprint(&key.verifying_key()),
+            value: BASE64.encode(key.sign(&bytes).to_bytes()),
+        });
+        Ok(signed)
+    }
+
+    /// Check the signature of an external manifest's JSON
+    pub fn verify(&self, json: &str) -> ManifestSignatureCheck {
+        let Ok(value) = serde_json::from_str::<Value>(json) else {
+            return ManifestSignatureCheck::new(SignatureStatus::InvalidSignature, None);
+        };
+        let Some(signature) = value.get(SIGNATURE_FIELD).filter(|s| !s.is_null()) else {
+            return ManifestSignatureCheck::new(SignatureStatus::Unsigned, None);
+        };
+        let Ok(signature) = serde_json::from_value::<ManifestSignature>(signature.clone()) else {
+            return ManifestSignatureCheck::new(SignatureStatus::InvalidSignature, None);
+        };
+        let signer = Some(signature.signer.as_str());
+
+        let Some(public_key) = self.known_public_key(&signature.signer) else {
+            return ManifestSignatureCheck::new(SignatureStatus::UnknownSigner, signer);
+        };
+
+        let valid = signature.algorithm == SIGNATURE_ALGORITHM
+            && BASE64
+                .decode(&signature.value)
+                .ok()
+                .and_then(|bytes| Signature::from_slice(&bytes).ok())
+                .is_some_and(|sig| {
+                    public_key
+                        .verify_strict(&canonical_bytes(&value), &sig)
+                        .is_ok()
+                });
+        let status = if valid {
+            SignatureStatus::ValidSignature
+        } else {
+            SignatureStatus::InvalidSignature
+        };
+        ManifestSignatureCheck::new(status, signer)
+    }
+
+    /// Check the signature of the external manifest at `path`
+    pub fn verify_file(&self, path: &Path) -> Result<ManifestSignatureCheck, StorageError> {
+        let json = fs::read_to_string(path).map_err(|e| StorageError::FileReadFailed {
+            path: path.to_path_buf(),
+            source: e,
+        })?;
+        Ok(self.verify(&json))
+    }
+
+    fn signing_key(&self, vault_id: &str) -> Result<SigningKey, StorageError> {
+        let path = self.signing_key_path(vault_id)?;
+        if path.exists() {
+            let hex_seed = Zeroizing::new(fs::read_to_string(&path).map_err(|e| {
+                StorageError::FileReadFailed {
+                    path: path.clone(),
+                    source: e,
+                }
+            })?);
+            let seed =
+                Zeroizing::new(hex::decode(hex_seed.trim()).map_err(|e| {
+                    StorageError::FileCorruption(format!("Manifest signing key: {e}"))
+                })?);
+            let seed: &[u8; 32] = seed.as_slice().try_into().map_err(|_| {
+                StorageError::FileCorruption("Manifest signing key has the wrong length".into())
+            })?;
+            return Ok(SigningKey::from_bytes(seed));
+        }
+
+        let mut seed = Zeroizing::new([0u8; 32]);
+        rand::rngs::OsRng.fill_bytes(&mut *seed);
+        let key = SigningKey::from_bytes(&seed);
+
+        fs::create_dir_all(self.keys_dir.join(SIGNING_KEYS_DIRNAME))?;
+        write_private(&path, Zeroizing::new(hex::encode(&seed[..])).as_bytes())?;
+        self.register_signer(vault_id, &key.verifying_key())?;
+
+        info!(vault_id, "Created manifest signing key");
+        Ok(key)
+    }
+
+    fn signing_key_path(&self, vault_id: &str) -> Result<PathBuf, StorageError> {
+        let safe = !vault_id.is_empty()
+            && vault_id
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+        if !safe {
+            return Err(StorageError::InvalidVaultName(vault_id.to_string()));
+        }
+        Ok(self
+            .keys_dir
+            .join(SIGNING_KEYS_DIRNAME)
+            .join(format!("{vault_id}.key")))
+    }
+
+    fn signers_path(&self) -> PathBuf {
+        self.keys_dir.join(SIGNERS_FILENAME)
+    }
+
+    fn load_signers(&self) -> SignerRegistry {
+        let path = self.signers_path();
+        if !path.exists() {
+            return SignerRegistry::default();
+        }
+        match fs::read_to_string(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|c| serde_json::from_str(&c).map_err(|e| e.to_string()))
+        {
+            Ok(registry) => registry,
+            Err(e) => {
+                warn!(path = %path.display(), error = %e, "Failed to load manifest signers");
+                SignerRegistry::default()
+            }
+        }
+    }
+
+    fn register_signer(
+        &self,
+        vault_id: &str,
+        public_key: &VerifyingKey,
+    ) -> Result<(), StorageError> {
+        let mut registry = self.load_signers();
+        let fingerprint = fingerprint(public_key);
+        registry.signers.retain(|s| s.fingerprint != fingerprint);
+        registry.signers.push(KnownSigner {
+            fingerprint,
+            vault_id: vault_id.to_string(),
+            public_key: hex::encode(public_key.as_bytes()),
+            created_at: Utc::now(),
+        });
+
+        let path = self.signers_path();
+        let json = serde_json::to_string_pretty(&registry)?;
+        atomic_write_sync(&path, json.as_bytes()).map_err(|e| StorageError::FileWriteFailed {
+            path,
+            source: std::io::Error::other(e),
+        })
+    }
+
+    fn known_public_key(&self, fingerprint: &str) -> Option<VerifyingKey> {
+        let signer = self
+            .load_signers()
+            .signers
+            .into_iter()
+            .find(|s| s.fingerprint == fingerprint)?;
+        let bytes: [u8; 32] = hex::decode(&signer.public_key).ok()?.try_into().ok()?;
+        VerifyingKey::from_bytes(&bytes).ok()
+    }
+}
+
+/// Serialize a manifest for the external sidecar, signed when possible
+///
+/// A manifest that can't be signed (e.g. the keys directory is unavailable)
+/// is written unsigned rather than with a stale signature.
+pub fn signed_manifest_json(manifest: &VaultMetadata) -> Result<String, StorageError> {
+    let signed = match ManifestSigner::from_keys_dir().and_then(|signer| signer.sign(manifest)) {
+        Ok(signed) => signed,
+        Err(e) => {
+            warn!(vault = %manifest.label(), error = %e, "Writing external manifest unsigned");
+            let mut unsigned = manifest.clone();
+            unsigned.signature = None;
+            unsigned
+        }
+    };
+    debug!(vault = %signed.label(), signer = ?signed.signer_fingerprint, "Serialized manifest");
+    serde_json::to_string_pretty(&signed).map_err(|e| StorageError::SerializationFailed {
+        message: format!("Failed to serialize manifest: {}", e),
+    })
+}
+
+/// Fingerprint of a signing public key: `ed25519:` and 32 hex digits
+fn fingerprint(public_key: &VerifyingKey) -> String {
+    let digest = Sha256::digest(public_key.as_bytes());
+    format!("{SIGNATURE_ALGORITHM}:{}", hex::encode(&digest[..16]))
+}
+
+/// Bytes a signature covers: sorted keys, no whitespace, no signature field
+fn canonical_bytes(value: &Value) -> Vec<u8> {
+    let mut value = value.clone();
+    if let Value::Object(map) = &mut value {
+        map.remove(SIGNATURE_FIELD);
+    }
+    let mut out = String::new();
+    write_canonical(&value, &mut out);
+    out.into_bytes()
+}
+
+fn write_canonical(value: &Value, out: &mut String) {
+    match value {
+        Value::Object(map) => {
+            let mut keys: Vec<&String> = map.keys().collect();
+            keys.sort();
+            out.push('{');
+            for (i, key) in keys.into_iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                out.push_str(&Value::String(key.clone()).to_string());
+                out.push(':');
+                write_canonical(&map[key], out);
+            }
+            out.push('}');
+        }
+        Value::Array(items) => {
+            out.push('[');
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_canonical(item, out);
+            }
+            out.push(']');
+        }
+        other => out.push_str(&other.to_string()),
+    }
+}
+
+fn write_private(path: &Path, contents: &[u8]) -> Result<(), StorageError> {
+    atomic_write_sync(path, contents).map_err(|e| StorageError::FileWriteFailed {
+        path: path.to_path_buf(),
+        source: std::io::Error::other(e),
+    })?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(path, fs::Permissions::from_mode(0o600))?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::file::infrastructure::file_operations::{HashAlgorithm, RawPath};
+    use crate::services::shared::infrastructure::DeviceInfo;
+    use crate::services::vault::infrastructure::persistence::metadata::VaultFileEntry;
+    use tempfile::TempDir;
+
+    fn manifest() -> VaultMetadata {
+        let device_info = DeviceInfo {
+            machine_id: "test-machine".to_string(),
+            machine_label: "test-laptop".to_string(),
+            created_at: Utc::now(),
+            app_version: "2.0.0".to_string(),
+        };
+        let files = vec![VaultFileEntry {
+            path: "will.pdf".to_string(),
+            raw_path: RawPath::from_display("will.pdf"),
+            lossy_name: false,
+            size: 1024,
+            sha256: "a".repeat(64),
+            hash_algorithm: HashAlgorithm::Sha256,
+            digest: None,
+            ownership: None,
+            content_type: None,
+            content_type_mismatch: false,
+            transform: None,
+        }];
+        VaultMetadata::new(
+            "vault-001".to_string(),
+            "Family Docs".to_string(),
+            None,
+            "Family-Docs".to_string(),
+            &device_info,
+            None,
+            vec![],
+            files,
+            1,
+            1024,
+        )
+    }
+
+    fn signed_json(signer: &ManifestSigner) -> String {
+        let signed = signer.sign(&manifest()).unwrap();
+        serde_json::to_string_pretty(&signed).unwrap()
+    }
+
+    #[test]
+    fn test_signed_manifest_verifies() {
+        let dir = TempDir::new().unwrap();
+        let signer = ManifestSigner::new(dir.path());
+        let json = signed_json(&signer);
+
+        let check = signer.verify(&json);
+        assert_eq!(check.status, SignatureStatus::ValidSignature);
+        assert_eq!(
+            check.signer,
+            Some(signer.fingerprint_for("vault-001").unwrap())
+        );
+
+        // Re-serialized with different formatting, the signature still holds
+        let compact = serde_json::to_string(&serde_json::from_str::<Value>(&json).unwrap());
+        let check = signer.verify(&compact.unwrap());
+        assert_eq!(check.status, SignatureStatus::ValidSignature);
+    }
+
+    #[test]
+    fn test_tampered_hash_is_invalid() {
+        let dir = TempDir::new().unwrap();
+        let signer = ManifestSigner::new(dir.path());
+        let json = signed_json(&signer);
+
+        let mut value: Value = serde_json::from_str(&json).unwrap();
+        value["content"]["files"][0]["sha256"] = Value::String("b".repeat(64));
+        let tampered = serde_json::to_string_pretty(&value).unwrap();
+
+        let check = signer.verify(&tampered);
+        assert_eq!(check.status, SignatureStatus::InvalidSignature);
+        assert!(!check.is_acceptable());
+    }
+
+    #[test]
+    fn test_legacy_unsigned_manifest_is_accepted() {
+        let dir = TempDir::new().unwrap();
+        let signer = ManifestSigner::new(dir.path());
+        let json = serde_json::to_string_pretty(&manifest()).unwrap();
+
+        let check = signer.verify(&json);
+        assert_eq!(check.status, SignatureStatus::Unsigned);
+        assert!(check.is_acceptable());
+        assert!(check.notice().is_some());
+    }
+
+    #[test]
+    fn test_unknown_signer_is_detected() {
+        let other_device = TempDir::new().unwrap();
+        let json = signed_json(&ManifestSigner::new(other_device.path()));
+
+        // This device has never seen the signing key
+        let dir = TempDir::new().unwrap();
+        let check = ManifestSigner::new(dir.path()).verify(&json);
+        assert_eq!(check.status, SignatureStatus::UnknownSigner);
+        assert!(check.signer.is_some());
+    }
+
+    #[test]
+    fn test_signer_is_recorded_in_manifest() {
+        let dir = TempDir::new().unwrap();
+        let signer = ManifestSigner::new(dir.path());
+
+        let signed = signer.sign(&manifest()).unwrap();
+        let signature = signed.signature.as_ref().unwrap();
+        assert_eq!(
+            signed.signer_fingerprint.as_deref(),
+            Some(signature.signer.as_str())
+        );
+    }
+
+    #[test]
+    fn test_signing_key_is_reused() {
+        let dir = TempDir::new().unwrap();
+        let signer = ManifestSigner::new(dir.path());
+
+        let first = signer.fingerprint_for("vault-001").unwrap();
+        assert_eq!(signer.fingerprint_for("vault-001").unwrap(), first);
+        assert_ne!(signer.fingerprint_for("vault-002").unwrap(), first);
+        assert!(signer.fingerprint_for("../escape").is_err());
+    }
+}
@@ -3,12 +3,41 @@
 //! This module implements the metadata structure that supports
 //! multiple recipients including both passphrase and YubiKey protection modes.
 
+use crate::services::file::infrastructure::file_operations::{
+    AppliedTransform, ArchiveSafetyLimits, FileOpsError, FileOwnership, HashAlgorithm, RawPath,
+    SkippedEntry, TransformedFile, calculate_file_hash_with,
+};
 use crate::services::key_management::yubikey::domain::models::ProtectionMode;
+use crate::services::shared::infrastructure::ClockService;
 use crate::services::shared::infrastructure::DeviceInfo as MachineDeviceInfo;
-use crate::services::vault::domain::models::VaultSummary;
+use crate::services::vault::domain::models::{
+    AppRequirements, AppliedTemplate, ArchiveFeature, GenerationStamp, VaultItem, VaultSummary,
+    VaultSyncStatus,
+};
+use crate::services::vault::infrastructure::persistence::manifest_signing::ManifestSignature;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+
+/// Schema written by this app
+///
+/// Version 3 added directory entries; older manifests have none recorded, so
+/// directory checks are skipped for them. Version 4 records each file's hash
+/// algorithm; entries from older manifests load as SHA-256. Version 5 records
+/// each entry's exact name beside its display path; older entries load with
+/// their display path as the exact name.
+pub const MANIFEST_SCHEMA: &str = "barqly.vault.manifest/5";
+
+const MANIFEST_SCHEMA_PREFIX: &str = "barqly.vault.manifest/";
+
+/// First schema version that records directory entries
+const DIRECTORY_ENTRIES_SCHEMA_VERSION: u32 = 3;
+
+/// First schema version that records a hash algorithm per file
+const HASH_ALGORITHM_SCHEMA_VERSION: u32 = 4;
+
+/// First schema version that records raw entry paths
+const RAW_PATH_SCHEMA_VERSION: u32 = 5;
 
 /// Bundle type for distinguishing backup from share scenarios
 ///
@@ -25,7 +54,7 @@ pub enum BundleType {
 /// Vault metadata supporting multiple protection modes (Schema v2 - Nested structure)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct VaultMetadata {
-    pub schema: String, // "barqly.vault.manifest/2"
+    pub schema: String, // "barqly.vault.manifest/5"
     pub vault: VaultInfo,
     pub versioning: Versioning,
     pub encryption: EncryptionConfig,
@@ -35,6 +64,41 @@ pub struct VaultMetadata {
     /// Bundle type: backup (full recovery) or shared (for recipients)
     #[serde(default)]
     pub bundle_type: BundleType,
+    /// Template applied at vault creation (snapshot of policy and checklist)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub template: Option<AppliedTemplate>,
+    /// Structured inheritance checklist entries (linked files, notes)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub items: Vec<VaultItem>,
+    /// Source files left out of this revision because they couldn't be read
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub skipped_entries: Vec<SkippedEntry>,
+    /// User comment for this encryption (external manifest only, never embedded)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub comment: Option<String>,
+    /// When the comment was last edited after encryption
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub comment_updated_at: Option<DateTime<Utc>>,
+    /// Lowest app version that understands everything in this archive
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_app_version: Option<String>,
+    /// Lowest app version that can open this archive at all
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub required_app_version: Option<String>,
+    /// Fingerprint of the key the external manifest is signed with
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signer_fingerprint: Option<String>,
+    /// Detached signature (external manifest only, never embedded)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<ManifestSignature>,
+    /// Hash of this vault's newest operation log entry, so a log cut short
+    /// or rewritten from the end no longer matches
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub operation_log_head: Option<String>,
+    /// Generation of this manifest file and the device that wrote it,
+    /// stamped on every write
+    #[serde(flatten)]
+    pub generation_stamp: GenerationStamp,
 }
 
 /// Machine information for tracking vault operations across devices
@@ -69,6 +133,9 @@ pub struct Versioning {
 pub struct EncryptionInfo {
     pub at: DateTime<Utc>,
     pub by: LastEncryptedBy,
+    /// `at` was read from an implausible system clock
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub clock_unreliable: bool,
 }
 
 /// Encryption configuration (Schema v2)
@@ -76,17 +143,82 @@ pub struct EncryptionInfo {
 pub struct EncryptionConfig {
     pub method: String,
     pub recipients: Vec<RecipientInfo>,
+    /// Contacts this archive was also encrypted to; never vault keys
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub contacts: Vec<ContactRecipientInfo>,
+    /// Safety limits the archive was created within; archives without them
+    /// are pre-scanned before they are extracted
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub safety_limits: Option<ArchiveSafetyLimits>,
+}
+
+/// A contact an archive was encrypted to, as it was at encryption time
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ContactRecipientInfo {
+    pub contact_id: String,
+    pub name: String,
+    pub public_key: String,
+    pub fingerprint: String,
+}
+
+/// Who an archive is encrypted to, for display
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+pub struct RecipientAttribution {
+    pub vault_keys: usize,
+    /// Contact names, in encryption order
+    pub contacts: Vec<String>,
+    /// e.g. "2 vault keys + contact 'Sam'"
+    pub summary: String,
 }
 
 /// Content and file information (Schema v2)
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(from = "StoredContentInfo")]
 pub struct ContentInfo {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub source_root: Option<String>, // Was base_path
     pub files: Vec<VaultFileEntry>,
+    /// Directories of a folder selection, including empty ones (schema 3+)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub directories: Vec<VaultDirectoryEntry>,
     pub stats: ContentStats,
 }
 
+/// `ContentInfo` as stored
+///
+/// Entries written before raw paths were recorded get their display path as
+/// the raw path, which is all that was ever known about them.
+#[derive(Deserialize)]
+struct StoredContentInfo {
+    source_root: Option<String>,
+    files: Vec<VaultFileEntry>,
+    #[serde(default)]
+    directories: Vec<VaultDirectoryEntry>,
+    stats: ContentStats,
+}
+
+impl From<StoredContentInfo> for ContentInfo {
+    fn from(stored: StoredContentInfo) -> Self {
+        let mut content = Self {
+            source_root: stored.source_root,
+            files: stored.files,
+            directories: stored.directories,
+            stats: stored.stats,
+        };
+        for file in content.files.iter_mut().filter(|f| f.raw_path.is_empty()) {
+            file.raw_path = RawPath::from_display(&file.path);
+        }
+        for directory in content
+            .directories
+            .iter_mut()
+            .filter(|d| d.raw_path.is_empty())
+        {
+            directory.raw_path = RawPath::from_display(&directory.path);
+        }
+        content
+    }
+}
+
 /// Content statistics (Schema v2)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ContentStats {
@@ -105,9 +237,142 @@ pub enum SelectionType {
 /// File entry in the vault with integrity information
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct VaultFileEntry {
-    pub path: String, // Relative path from base_path
+    pub path: String, // Relative path from base_path, for display
+    /// The relative path exactly as named on disk (schema 5+)
+    #[serde(default, skip_serializing_if = "RawPath::is_empty")]
+    pub raw_path: RawPath,
+    /// `path` isn't the true name: it held bytes that aren't valid Unicode
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub lossy_name: bool,
     pub size: u64,
-    pub sha256: String, // File hash for verification
+    pub sha256: String, // Also matches files across archives, whatever the algorithm
+    /// Algorithm the file is verified with
+    #[serde(default)]
+    pub hash_algorithm: HashAlgorithm,
+    /// Digest under `hash_algorithm`, when that isn't SHA-256
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub digest: Option<String>,
+    /// Owner names and IDs at archive time (Unix only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ownership: Option<FileOwnership>,
+    /// Type detected from the file's content, e.g. `application/pdf`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content_type: Option<String>,
+    /// The file's extension doesn't match its detected content type
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub content_type_mismatch: bool,
+    /// The source a pre-encryption transform made this file from; `size`
+    /// and the hashes above describe the transformed file
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transform: Option<AppliedTransform>,
+}
+
+impl VaultFileEntry {
+    /// Relative path of the source file, before a transform renamed it
+    pub fn source_path(&self) -> &str {
+        self.transform
+            .as_ref()
+            .and_then(|t| t.original_path.as_deref())
+            .unwrap_or(&self.path)
+    }
+
+    /// SHA-256 of the source file, before a transform changed it
+    pub fn source_sha256(&self) -> &str {
+        self.transform
+            .as_ref()
+            .map_or(&self.sha256, |t| &t.original_sha256)
+    }
+
+    /// Describe the transformed copy `file` instead of the source, keeping
+    /// the source's path, size and SHA-256 as provenance
+    ///
+    /// Only the SHA-256 is updated; call `rehash` on the copy for another
+    /// algorithm.
+    pub fn record_transform(&mut self, file: &TransformedFile) {
+        let renamed = file.relative_path != self.path;
+        self.transform = Some(AppliedTransform {
+            transform: file.transform,
+            original_path: renamed.then(|| self.path.clone()),
+            original_size: self.size,
+            original_sha256: std::mem::replace(&mut self.sha256, file.sha256.clone()),
+        });
+        if renamed {
+            self.path = file.relative_path.clone();
+            self.raw_path = RawPath::from_display(&file.relative_path);
+        }
+        self.size = file.size;
+        if let Some(content_type) = file.transform.output_content_type() {
+            self.content_type = Some(content_type.to_string());
+            self.content_type_mismatch = false;
+        }
+    }
+
+    /// True relative path, falling back to the display path when the raw
+    /// path is missing or can't be held on this platform
+    pub fn relative_path(&self) -> PathBuf {
+        entry_relative_path(&self.raw_path, &self.path)
+    }
+
+    /// Digest the file is verified against, under `hash_algorithm`
+    pub fn verification_digest(&self) -> &str {
+        match self.hash_algorithm {
+            HashAlgorithm::Sha256 => &self.sha256,
+            _ => self.digest.as_deref().unwrap_or_default(),
+        }
+    }
+
+    /// Record the digest of `path` under `algorithm`
+    pub fn rehash(&mut self, path: &Path, algorithm: HashAlgorithm) -> Result<(), FileOpsError> {
+        self.digest = match algorithm {
+            HashAlgorithm::Sha256 => None,
+            other => Some(calculate_file_hash_with(path, other)?),
+        };
+        self.hash_algorithm = algorithm;
+        Ok(())
+    }
+
+    /// Whether the file at `path` matches, hashed with this entry's algorithm
+    pub fn verify_file(&self, path: &Path) -> Result<bool, FileOpsError> {
+        let expected = self.verification_digest();
+        if expected.is_empty() {
+            return Ok(false);
+        }
+        let actual = calculate_file_hash_with(path, self.hash_algorithm)?;
+        Ok(actual.eq_ignore_ascii_case(expected))
+    }
+}
+
+/// Directory entry in the vault; checked for existence, never hashed
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct VaultDirectoryEntry {
+    pub path: String, // Relative path from base_path, for display
+    /// The relative path exactly as named on disk (schema 5+)
+    #[serde(default, skip_serializing_if = "RawPath::is_empty")]
+    pub raw_path: RawPath,
+    /// `path` isn't the true name: it held bytes that aren't valid Unicode
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub lossy_name: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub modified: Option<DateTime<Utc>>,
+    /// Mode bits (Unix only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub permissions: Option<u32>,
+}
+
+impl VaultDirectoryEntry {
+    /// True relative path, as for `VaultFileEntry::relative_path`
+    pub fn relative_path(&self) -> PathBuf {
+        entry_relative_path(&self.raw_path, &self.path)
+    }
+}
+
+fn entry_relative_path(raw_path: &RawPath, display: &str) -> PathBuf {
+    raw_path.to_relative().unwrap_or_else(|| {
+        display
+            .split(['/', '\\'])
+            .filter(|c| !c.is_empty())
+            .collect()
+    })
 }
 
 /// Optional integrity verification hashes
@@ -170,7 +435,7 @@ pub fn new(
         let now = Utc::now();
 
         Self {
-            schema: "barqly.vault.manifest/2".to_string(),
+            schema: MANIFEST_SCHEMA.to_string(),
             vault: VaultInfo {
                 id: vault_id,
                 label,
@@ -185,10 +450,13 @@ pub fn new(
             encryption: EncryptionConfig {
                 method: "age".to_string(),
                 recipients,
+                contacts: Vec::new(),
+                safety_limits: Some(ArchiveSafetyLimits::default()),
             },
             content: ContentInfo {
                 source_root,
                 files,
+                directories: Vec::new(),
                 stats: ContentStats {
                     count: file_count,
                     total_bytes: total_size,
@@ -196,7 +464,100 @@ pub fn new(
             },
             integrity: None,
             bundle_type: BundleType::Backup,
+            template: None,
+            items: Vec::new(),
+            skipped_entries: Vec::new(),
+            comment: None,
+            comment_updated_at: None,
+            min_app_version: None,
+            required_app_version: None,
+            signer_fingerprint: None,
+            signature: None,
+            operation_log_head: None,
+            generation_stamp: GenerationStamp::default(),
+        }
+    }
+
+    /// Carry over vault-level settings from a previously saved manifest
+    ///
+    /// Encryption rebuilds the manifest from the vault and registry; fields that
+    /// describe the vault itself (rather than the latest archive) must survive.
+    pub fn inherit_vault_settings(&mut self, existing: &VaultMetadata) {
+        self.template = existing.template.clone();
+        self.items = existing.items.clone();
+        self.operation_log_head = existing.operation_log_head.clone();
+    }
+
+    /// Keep `existing`'s transform records for files archived again as they
+    /// were, e.g. by a migration re-archiving already transformed files
+    pub fn inherit_transforms(&mut self, existing: &VaultMetadata) {
+        for file in self
+            .content
+            .files
+            .iter_mut()
+            .filter(|f| f.transform.is_none())
+        {
+            file.transform = existing
+                .content
+                .files
+                .iter()
+                .find(|old| old.path == file.path && old.sha256.eq_ignore_ascii_case(&file.sha256))
+                .and_then(|old| old.transform.clone());
+        }
+    }
+
+    /// Optional features this manifest's archive uses
+    pub fn archive_features(&self) -> Vec<ArchiveFeature> {
+        let mut features = Vec::new();
+        if self.bundle_type == BundleType::Backup {
+            features.push(ArchiveFeature::EmbeddedManifest);
+        }
+        if self.content.files.iter().any(|f| f.ownership.is_some()) {
+            features.push(ArchiveFeature::FileOwnership);
+        }
+        if !self.skipped_entries.is_empty() {
+            features.push(ArchiveFeature::SkippedEntries);
+        }
+        if self.template.is_some() {
+            features.push(ArchiveFeature::VaultTemplate);
+        }
+        if !self.items.is_empty() {
+            features.push(ArchiveFeature::VaultItems);
         }
+        if !self.content.directories.is_empty() {
+            features.push(ArchiveFeature::DirectoryEntries);
+        }
+        if self
+            .content
+            .files
+            .iter()
+            .any(|f| f.hash_algorithm != HashAlgorithm::Sha256)
+        {
+            features.push(ArchiveFeature::FileDigests);
+        }
+        if self.content.files.iter().any(|f| f.lossy_name)
+            || self.content.directories.iter().any(|d| d.lossy_name)
+        {
+            features.push(ArchiveFeature::RawFileNames);
+        }
+        features
+    }
+
+    /// Record the app versions needed for the features this archive uses
+    ///
+    /// Call once the manifest's content is final, before it is embedded.
+    pub fn stamp_app_requirements(&mut self) {
+        let requirements = AppRequirements::for_features(&self.archive_features());
+        self.min_app_version = Some(requirements.min.to_string());
+        self.required_app_version = Some(requirements.required.to_string());
+    }
+
+    /// App versions this manifest says are needed
+    pub fn app_requirements(&self) -> AppRequirements {
+        AppRequirements::from_recorded(
+            self.min_app_version.as_deref(),
+            self.required_app_version.as_deref(),
+        )
     }
 
     // Helper methods for backward compatibility (minimize changes to calling code)
@@ -220,6 +581,93 @@ pub fn file_count(&self) -> usize {
         self.content.stats.count
     }
 
+    /// Version number of the manifest schema, e.g. 4 for `barqly.vault.manifest/4`
+    pub fn schema_version(&self) -> Option<u32> {
+        self.schema
+            .strip_prefix(MANIFEST_SCHEMA_PREFIX)?
+            .parse()
+            .ok()
+    }
+
+    /// Whether directories were recorded, so their absence means something
+    pub fn records_directories(&self) -> bool {
+        self.schema_version()
+            .is_some_and(|version| version >= DIRECTORY_ENTRIES_SCHEMA_VERSION)
+    }
+
+    /// Whether file entries record their hash algorithm
+    ///
+    /// Entries from older schemas were all hashed with SHA-256.
+    pub fn records_hash_algorithms(&self) -> bool {
+        self.schema_version()
+            .is_some_and(|version| version >= HASH_ALGORITHM_SCHEMA_VERSION)
+    }
+
+    /// Whether entries were written with their exact names
+    ///
+    /// Older entries carry their display path as the raw path, so a name
+    /// that wasn't valid Unicode is only known with replacement characters.
+    pub fn records_raw_paths(&self) -> bool {
+        self.schema_version()
+            .is_some_and(|version| version >= RAW_PATH_SCHEMA_VERSION)
+    }
+
+    /// Recorded directories as paths inside the archive
+    ///
+    /// A folder selection is archived under the folder's name. `None` for
+    /// manifests that predate directory entries.
+    pub fn archived_directory_paths(&self) -> Option<Vec<PathBuf>> {
+        if !self.records_directories() {
+            return None;
+        }
+
+        Some(
+            self.content
+                .directories
+                .iter()
+                .map(|directory| self.archived_path(&directory.relative_path()))
+                .collect(),
+        )
+    }
+
+    /// Path of a file entry inside the archive
+    pub fn archived_file_path(&self, entry: &VaultFileEntry) -> PathBuf {
+        self.archived_path(&entry.relative_path())
+    }
+
+    /// Archived paths of entries whose names aren't valid Unicode
+    ///
+    /// The archive stores these under `archive_entry_name`; extraction
+    /// renames them back with `restore_true_names`.
+    pub fn lossy_archived_paths(&self) -> Vec<PathBuf> {
+        let files = self
+            .content
+            .files
+            .iter()
+            .filter(|f| f.lossy_name)
+            .map(|f| f.relative_path());
+        let directories = self
+            .content
+            .directories
+            .iter()
+            .filter(|d| d.lossy_name)
+            .map(|d| d.relative_path());
+        files
+            .chain(directories)
+            .map(|relative| self.archived_path(&relative))
+            .collect()
+    }
+
+    fn archived_path(&self, relative: &Path) -> PathBuf {
+        self.content
+            .source_root
+            .as_deref()
+            .and_then(|root| Path::new(root).file_name())
+            .map(PathBuf::from)
+            .unwrap_or_default()
+            .join(relative)
+    }
+
     pub fn total_size(&self) -> u64 {
         self.content.stats.total_bytes
     }
@@ -244,15 +692,61 @@ pub fn recipients_mut(&mut self) -> &mut Vec<RecipientInfo> {
         &mut self.encryption.recipients
     }
 
+    pub fn contacts(&self) -> &[ContactRecipientInfo] {
+        &self.encryption.contacts
+    }
+
+    /// Vault keys and contacts the archive is encrypted to
+    pub fn recipient_attribution(&self) -> RecipientAttribution {
+        let vault_keys = self.encryption.recipients.len();
+        let contacts: Vec<String> = self
+            .encryption
+            .contacts
+            .iter()
+            .map(|contact| contact.name.clone())
+            .collect();
+
+        let mut summary = format!(
+            "{vault_keys} vault key{}",
+            if vault_keys == 1 { "" } else { "s" }
+        );
+        if !contacts.is_empty() {
+            let names = contacts
+                .iter()
+                .map(|name| format!("'{name}'"))
+                .collect::<Vec<_>>()
+                .join(", ");
+            let noun = if contacts.len() == 1 {
+                "contact"
+            } else {
+                "contacts"
+            };
+            summary.push_str(&format!(" + {noun} {names}"));
+        }
+
+        RecipientAttribution {
+            vault_keys,
+            contacts,
+            summary,
+        }
+    }
+
     /// Increment manifest version (for re-encryption)
     pub fn increment_version(&mut self, device_info: &MachineDeviceInfo) {
+        self.increment_version_at(device_info, ClockService::global());
+    }
+
+    /// Increment the revision, recording the raw clock and whether it's plausible
+    pub fn increment_version_at(&mut self, device_info: &MachineDeviceInfo, clock: &ClockService) {
+        let stamp = clock.stamp();
         self.versioning.revision += 1;
         self.versioning.last_encrypted = Some(EncryptionInfo {
-            at: Utc::now(),
+            at: stamp.at,
             by: LastEncryptedBy {
                 machine_id: device_info.machine_id.clone(),
                 machine_label: device_info.machine_label.clone(),
             },
+            clock_unreliable: !stamp.reliable,
         });
     }
 
@@ -371,7 +865,7 @@ pub fn has_passphrase_fallback(&self) -> bool {
     /// Validate metadata consistency
     pub fn validate(&self) -> Result<(), MetadataValidationError> {
         // Check schema version
-        if !self.schema.starts_with("barqly.vault.manifest/") {
+        if !self.schema.starts_with(MANIFEST_SCHEMA_PREFIX) {
             return Err(MetadataValidationError::InvalidVersion(self.schema.clone()));
         }
 
@@ -446,6 +940,9 @@ pub fn to_summary(&self) -> VaultSummary {
             description: self.vault.description.clone(),
             created_at: self.versioning.created_at,
             key_count: self.encryption.recipients.len(),
+            favorite: false,
+            sort_index: 0,
+            sync_status: VaultSyncStatus::InSync,
         }
     }
 
@@ -610,7 +1107,7 @@ pub fn is_valid_metadata(path: &PathBuf) -> bool {
             && let Ok(value) = serde_json::from_str::<serde_json::Value>(&content)
             && let Some(schema) = value.get("schema").and_then(|v| v.as_str())
         {
-            return schema.starts_with("barqly.vault.manifest/");
+            return schema.starts_with(MANIFEST_SCHEMA_PREFIX);
         }
         false
     }
@@ -619,6 +1116,7 @@ pub fn is_valid_metadata(path: &PathBuf) -> bool {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::services::file::infrastructure::file_operations::TransformKind;
     use crate::services::shared::infrastructure::DeviceInfo;
     use tempfile::TempDir;
 
@@ -663,7 +1161,7 @@ fn test_passphrase_only_metadata() {
 
         let metadata = create_test_metadata("vault-001", "Test Vault", vec![recipient]);
 
-        assert_eq!(metadata.schema, "barqly.vault.manifest/2");
+        assert_eq!(metadata.schema, MANIFEST_SCHEMA);
         assert!(metadata.has_passphrase_fallback());
         assert!(metadata.validate().is_ok());
     }
@@ -684,7 +1182,7 @@ fn test_yubikey_only_metadata() {
 
         let metadata = create_test_metadata("vault-002", "YubiKey Vault", vec![recipient]);
 
-        assert_eq!(metadata.schema, "barqly.vault.manifest/2");
+        assert_eq!(metadata.schema, MANIFEST_SCHEMA);
         assert!(!metadata.has_passphrase_fallback());
         assert!(metadata.validate().is_ok());
     }
@@ -716,7 +1214,7 @@ fn test_hybrid_mode_metadata() {
             vec![passphrase_recipient, yubikey_recipient],
         );
 
-        assert_eq!(metadata.schema, "barqly.vault.manifest/2");
+        assert_eq!(metadata.schema, MANIFEST_SCHEMA);
         assert!(metadata.has_passphrase_fallback());
         assert!(metadata.validate().is_ok());
         assert_eq!(metadata.recipients().len(), 2);
@@ -778,6 +1276,30 @@ fn test_encryption_revision_increment() {
         );
     }
 
+    #[test]
+    fn test_encryption_on_bad_clock_is_flagged() {
+        use crate::services::shared::infrastructure::clock::testing::{FakeClock, service};
+
+        let device_info = create_test_device_info();
+        let mut metadata = create_test_metadata("vault-007", "Clock Test", vec![]);
+        let clock = FakeClock::at("2026-03-01T00:00:00Z");
+
+        metadata.increment_version_at(&device_info, &service(&clock));
+        let json = serde_json::to_string(&metadata).unwrap();
+        assert!(!json.contains("clock_unreliable"));
+
+        // The raw clock is still recorded, just flagged
+        clock.set_wall("1970-01-01T00:00:00Z");
+        metadata.increment_version_at(&device_info, &service(&clock));
+        let encrypted = metadata.versioning.last_encrypted.as_ref().unwrap();
+        assert_eq!(encrypted.at, DateTime::<Utc>::UNIX_EPOCH);
+        assert!(encrypted.clock_unreliable);
+
+        let json = serde_json::to_string(&metadata).unwrap();
+        let parsed: VaultMetadata = serde_json::from_str(&json).unwrap();
+        assert!(parsed.versioning.last_encrypted.unwrap().clock_unreliable);
+    }
+
     #[test]
     fn test_version_comparison() {
         let device_info = create_test_device_info();
@@ -855,4 +1377,398 @@ fn test_bundle_type_default() {
         let default_type: BundleType = Default::default();
         assert_eq!(default_type, BundleType::Backup);
     }
+
+    #[test]
+    fn test_skipped_entries_round_trip() {
+        let mut metadata = create_test_metadata("vault-001", "Test Vault", vec![]);
+
+        let json = serde_json::to_string(&metadata).unwrap();
+        assert!(!json.contains("skipped_entries"));
+
+        metadata.skipped_entries.push(SkippedEntry {
+            path: "mail/inbox.pst".to_string(),
+            reason: "file is locked".to_string(),
+        });
+        let json = serde_json::to_string(&metadata).unwrap();
+        let parsed: VaultMetadata = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(parsed.skipped_entries, metadata.skipped_entries);
+    }
+
+    #[test]
+    fn test_safety_limits_recorded_and_optional() {
+        let mut metadata = create_test_metadata("vault-001", "Test Vault", vec![]);
+        assert_eq!(
+            metadata.encryption.safety_limits,
+            Some(ArchiveSafetyLimits::default())
+        );
+
+        let json = serde_json::to_string(&metadata).unwrap();
+        let parsed: VaultMetadata = serde_json::from_str(&json).unwrap();
+        assert_eq!(
+            parsed.encryption.safety_limits,
+            metadata.encryption.safety_limits
+        );
+
+        // Manifests written before the limits existed load without them
+        metadata.encryption.safety_limits = None;
+        let json = serde_json::to_string(&metadata).unwrap();
+        assert!(!json.contains("safety_limits"));
+        let parsed: VaultMetadata = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.encryption.safety_limits, None);
+    }
+
+    #[test]
+    fn test_app_requirements_stamped_from_features() {
+        let mut metadata = create_test_metadata("vault-001", "Test Vault", vec![]);
+        assert_eq!(
+            metadata.archive_features(),
+            [ArchiveFeature::EmbeddedManifest]
+        );
+
+        metadata.skipped_entries.push(SkippedEntry {
+            path: "mail/inbox.pst".to_string(),
+            reason: "file is locked".to_string(),
+        });
+        metadata.stamp_app_requirements();
+
+        let expected = AppRequirements::for_features(&[
+            ArchiveFeature::EmbeddedManifest,
+            ArchiveFeature::SkippedEntries,
+        ]);
+        assert_eq!(metadata.app_requirements(), expected);
+
+        let json = serde_json::to_string(&metadata).unwrap();
+        let parsed: VaultMetadata = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.min_app_version, Some(expected.min.to_string()));
+    }
+
+    #[test]
+    fn test_legacy_manifest_requires_baseline() {
+        let metadata = create_test_metadata("vault-001", "Test Vault", vec![]);
+
+        let json = serde_json::to_string(&metadata).unwrap();
+        assert!(!json.contains("min_app_version"));
+        assert_eq!(
+            metadata.app_requirements(),
+            AppRequirements::for_features(&[])
+        );
+    }
+
+    #[test]
+    fn test_recipient_attribution_names_contacts() {
+        let passphrase = RecipientInfo::new_passphrase(
+            "key-a".to_string(),
+            "age1a".to_string(),
+            "key-a".to_string(),
+            "key-a.agekey.enc".to_string(),
+        );
+        let other = RecipientInfo::new_passphrase(
+            "key-b".to_string(),
+            "age1b".to_string(),
+            "key-b".to_string(),
+            "key-b.agekey.enc".to_string(),
+        );
+        let mut metadata = create_test_metadata("vault-009", "Shared", vec![passphrase, other]);
+        assert_eq!(metadata.recipient_attribution().summary, "2 vault keys");
+
+        metadata.encryption.contacts.push(ContactRecipientInfo {
+            contact_id: "contact-sam-1".to_string(),
+            name: "Sam".to_string(),
+            public_key: "age1sam".to_string(),
+            fingerprint: "AAAA-BBBB-CCCC-DDDD".to_string(),
+        });
+        let attribution = metadata.recipient_attribution();
+        assert_eq!(attribution.vault_keys, 2);
+        assert_eq!(attribution.contacts, vec!["Sam".to_string()]);
+        assert_eq!(attribution.summary, "2 vault keys + contact 'Sam'");
+
+        // Contacts round-trip, and old manifests without them still load
+        let json = serde_json::to_string(&metadata).unwrap();
+        let loaded: VaultMetadata = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded.contacts(), metadata.contacts());
+        assert!(!loaded.has_recipients());
+    }
+
+    #[test]
+    fn test_directory_entries_and_older_schemas() {
+        let recipient = RecipientInfo::new_passphrase(
+            "key-a".to_string(),
+            "age1a".to_string(),
+            "key-a".to_string(),
+            "key-a.agekey.enc".to_string(),
+        );
+        let mut metadata = create_test_metadata("vault-010", "Folders", vec![recipient]);
+        metadata.content.source_root = Some("Projects".to_string());
+        metadata.content.directories.push(VaultDirectoryEntry {
+            path: r"empty\nested".to_string(),
+            raw_path: RawPath::from_display(r"empty\nested"),
+            lossy_name: false,
+            modified: None,
+            permissions: Some(0o755),
+        });
+
+        assert_eq!(metadata.schema_version(), Some(5));
+        assert_eq!(
+            metadata.archived_directory_paths(),
+            Some(vec![PathBuf::from("Projects").join("empty").join("nested")])
+        );
+        assert!(
+            metadata
+                .archive_features()
+                .contains(&ArchiveFeature::DirectoryEntries)
+        );
+
+        // Schema 2 manifests recorded no directories, so none are checked
+        let mut json = serde_json::to_value(&metadata).unwrap();
+        json["schema"] = "barqly.vault.manifest/2".into();
+        json["content"]
+            .as_object_mut()
+            .unwrap()
+            .remove("directories");
+        let older: VaultMetadata = serde_json::from_value(json).unwrap();
+        assert!(older.content.directories.is_empty());
+        assert!(!older.records_directories());
+        assert_eq!(older.archived_directory_paths(), None);
+    }
+
+    #[test]
+    fn test_file_entries_verify_with_their_own_algorithm() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("will.pdf");
+        std::fs::write(&path, b"last will").unwrap();
+
+        let mut entry = VaultFileEntry {
+            path: "will.pdf".to_string(),
+            raw_path: RawPath::from_display("will.pdf"),
+            lossy_name: false,
+            size: 9,
+            sha256: calculate_file_hash_with(&path, HashAlgorithm::Sha256).unwrap(),
+            hash_algorithm: HashAlgorithm::Sha256,
+            digest: None,
+            ownership: None,
+            content_type: None,
+            content_type_mismatch: false,
+            transform: None,
+        };
+        assert!(entry.verify_file(&path).unwrap());
+
+        entry.rehash(&path, HashAlgorithm::Sha512).unwrap();
+        assert_eq!(entry.verification_digest().len(), 128);
+        assert!(entry.verify_file(&path).unwrap());
+
+        // The SHA-512 digest is what gets checked, not the SHA-256 one
+        entry.digest = Some(entry.sha256.clone());
+        assert!(!entry.verify_file(&path).unwrap());
+
+        // Back to SHA-256, checked against the recorded SHA-256
+        entry.rehash(&path, HashAlgorithm::Sha256).unwrap();
+        assert!(entry.digest.is_none());
+        assert!(entry.verify_file(&path).unwrap());
+        std::fs::write(&path, b"altered will").unwrap();
+        assert!(!entry.verify_file(&path).unwrap());
+    }
+
+    #[test]
+    fn test_hash_algorithms_and_older_schemas() {
+        let recipient = RecipientInfo::new_passphrase(
+            "key-a".to_string(),
+            "age1a".to_string(),
+            "key-a".to_string(),
+            "key-a.agekey.enc".to_string(),
+        );
+        let mut metadata = create_test_metadata("vault-011", "Digests", vec![recipient]);
+        metadata.content.files.push(VaultFileEntry {
+            path: "deed.pdf".to_string(),
+            raw_path: RawPath::from_display("deed.pdf"),
+            lossy_name: false,
+            size: 4,
+            sha256: "a".repeat(64),
+            hash_algorithm: HashAlgorithm::Sha512,
+            digest: Some("b".repeat(128)),
+            ownership: None,
+            content_type: None,
+            content_type_mismatch: false,
+            transform: None,
+        });
+        assert!(metadata.records_hash_algorithms());
+        assert!(
+            metadata
+                .archive_features()
+                .contains(&ArchiveFeature::FileDigests)
+        );
+
+        let json = serde_json::to_value(&metadata).unwrap();
+        assert_eq!(json["content"]["files"][0]["hash_algorithm"], "sha512");
+
+        // Schema 3 entries carry no algorithm and load as SHA-256
+        let mut older = json.clone();
+        older["schema"] = "barqly.vault.manifest/3".into();
+        let file = older["content"]["files"][0].as_object_mut().unwrap();
+        file.remove("hash_algorithm");
+        file.remove("digest");
+        let older: VaultMetadata = serde_json::from_value(older).unwrap();
+        assert!(!older.records_hash_algorithms());
+        assert_eq!(older.content.files[0].hash_algorithm, HashAlgorithm::Sha256);
+        assert_eq!(older.content.files[0].verification_digest(), "a".repeat(64));
+        assert!(
+            !older
+                .archive_features()
+                .contains(&ArchiveFeature::FileDigests)
+        );
+    }
+
+    #[test]
+    fn test_older_entries_load_with_display_path_as_raw_path() {
+        let recipient = RecipientInfo::new_passphrase(
+            "key-a".to_string(),
+            "age1a".to_string(),
+            "key-a".to_string(),
+            "key-a.agekey.enc".to_string(),
+        );
+        let mut metadata = create_test_metadata("vault-012", "Names", vec![recipient]);
+        metadata.content.source_root = Some("Records".to_string());
+        metadata.content.files.push(VaultFileEntry {
+            path: r"taxes\50% done.pdf".to_string(),
+            raw_path: RawPath::from_display(r"taxes\50% done.pdf"),
+            lossy_name: false,
+            size: 4,
+            sha256: "a".repeat(64),
+            hash_algorithm: HashAlgorithm::Sha256,
+            digest: None,
+            ownership: None,
+            content_type: None,
+            content_type_mismatch: false,
+            transform: None,
+        });
+        assert!(metadata.records_raw_paths());
+
+        // Schema 4 entries carry only the display path
+        let mut json = serde_json::to_value(&metadata).unwrap();
+        assert_eq!(
+            json["content"]["files"][0]["raw_path"],
+            "taxes/50%25 done.pdf"
+        );
+        json["schema"] = "barqly.vault.manifest/4".into();
+        json["content"]["files"][0]
+            .as_object_mut()
+            .unwrap()
+            .remove("raw_path");
+        let older: VaultMetadata = serde_json::from_value(json).unwrap();
+        assert!(!older.records_raw_paths());
+        assert_eq!(older.content.files, metadata.content.files);
+        assert_eq!(
+            older.archived_file_path(&older.content.files[0]),
+            Path::new("Records").join("taxes").join("50% done.pdf")
+        );
+        assert!(older.lossy_archived_paths().is_empty());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_non_unicode_names_serialize_losslessly() {
+        use std::ffi::OsStr;
+        use std::os::unix::ffi::OsStrExt;
+
+        let recipient = RecipientInfo::new_passphrase(
+            "key-a".to_string(),
+            "age1a".to_string(),
+            "key-a".to_string(),
+            "key-a.agekey.enc".to_string(),
+        );
+        let mut metadata = create_test_metadata("vault-013", "Names", vec![recipient]);
+        let relative = Path::new(OsStr::from_bytes(b"r\xe9sum\xe9.txt"));
+        metadata.content.files.push(VaultFileEntry {
+            path: relative.to_string_lossy().into_owned(),
+            raw_path: RawPath::from_relative(relative),
+            lossy_name: true,
+            size: 4,
+            sha256: "a".repeat(64),
+            hash_algorithm: HashAlgorithm::Sha256,
+            digest: None,
+            ownership: None,
+            content_type: None,
+            content_type_mismatch: false,
+            transform: None,
+        });
+
+        let json = serde_json::to_string_pretty(&metadata).unwrap();
+        let loaded: VaultMetadata = serde_json::from_str(&json).unwrap();
+        assert_eq!(serde_json::to_string_pretty(&loaded).unwrap(), json);
+
+        let entry = &loaded.content.files[0];
+        assert_eq!(entry.path, "r\u{FFFD}sum\u{FFFD}.txt");
+        assert_eq!(entry.raw_path.as_str(), "r%E9sum%E9.txt");
+        assert!(entry.lossy_name);
+        assert_eq!(loaded.archived_file_path(entry), relative);
+        assert_eq!(loaded.lossy_archived_paths(), vec![relative.to_path_buf()]);
+        assert!(
+            loaded
+                .archive_features()
+                .contains(&ArchiveFeature::RawFileNames)
+        );
+    }
+
+    #[test]
+    fn test_transformed_entries_keep_source_provenance() {
+        let source_entry = VaultFileEntry {
+            path: "scans/deed.tiff".to_string(),
+            raw_path: RawPath::from_display("scans/deed.tiff"),
+            lossy_name: false,
+            size: 900,
+            sha256: "a".repeat(64),
+            hash_algorithm: HashAlgorithm::Sha256,
+            digest: None,
+            ownership: None,
+            content_type: Some("image/tiff".to_string()),
+            content_type_mismatch: false,
+            transform: None,
+        };
+        let mut entry = source_entry.clone();
+        entry.record_transform(&TransformedFile {
+            source_path: PathBuf::from("/docs/scans/deed.tiff"),
+            staged_path: PathBuf::from("/staging/0/out/deed.png"),
+            relative_path: "scans/deed.png".to_string(),
+            transform: TransformKind::ImageToPng,
+            size: 400,
+            sha256: "b".repeat(64),
+        });
+
+        assert_eq!(entry.path, "scans/deed.png");
+        assert_eq!(entry.raw_path.as_str(), "scans/deed.png");
+        assert_eq!(entry.size, 400);
+        assert_eq!(entry.sha256, "b".repeat(64));
+        assert_eq!(entry.content_type.as_deref(), Some("image/png"));
+        assert_eq!(entry.source_path(), "scans/deed.tiff");
+        assert_eq!(entry.source_sha256(), "a".repeat(64));
+        assert_eq!(source_entry.source_path(), "scans/deed.tiff");
+        assert_eq!(source_entry.source_sha256(), "a".repeat(64));
+
+        let recipient = RecipientInfo::new_passphrase(
+            "key-a".to_string(),
+            "age1a".to_string(),
+            "key-a".to_string(),
+            "key-a.agekey.enc".to_string(),
+        );
+        let mut metadata = create_test_metadata("vault-014", "Scans", vec![recipient]);
+        metadata.content.files.push(entry);
+        let json = serde_json::to_string(&metadata).unwrap();
+        let loaded: VaultMetadata = serde_json::from_str(&json).unwrap();
+        let transform = loaded.content.files[0].transform.as_ref().unwrap();
+        assert_eq!(transform.transform, TransformKind::ImageToPng);
+        assert_eq!(transform.original_size, 900);
+
+        // A migration rebuilds entries from the archived files; provenance carries over
+        let mut migrated = loaded.clone();
+        migrated.content.files[0].transform = None;
+        migrated.inherit_transforms(&loaded);
+        assert_eq!(migrated.content.files[0].source_path(), "scans/deed.tiff");
+
+        // Untransformed entries are written without the field
+        assert!(
+            !serde_json::to_string(&source_entry)
+                .unwrap()
+                .contains("transform")
+        );
+    }
 }
@@ -0,0 +1,578 @@
+//! Metadata snapshots
+//!
+//! Restore points for the key registry, the vault manifests and the archive
+//! index, taken before each mutating operation. File contents are stored once
+//! per SHA-256 as gzip objects, so a file that didn't change costs nothing in
+//! later snapshots; `index.json` lists each snapshot's files by hash.
+//!
+//! Retention keeps the newest `KEEP_RECENT` snapshots plus the newest one of
+//! each week for `KEEP_WEEKLY_FOR_DAYS`. Private key files are never captured.
+
+use crate::error::StorageError;
+use crate::services::key_management::shared::KeyRegistry;
+use crate::services::shared::infrastructure::io::atomic_write_sync;
+use crate::services::shared::infrastructure::path_management::{
+    get_app_dir, get_vaults_manifest_dir,
+};
+use crate::services::vault::infrastructure::persistence::ArchiveIndex;
+use chrono::{DateTime, Utc};
+use flate2::Compression;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tracing::{debug, warn};
+
+type SnapshotResult<T> = Result<T, Box<dyn std::error::Error + Send + Sync>>;
+
+const SNAPSHOTS_DIR: &str = "snapshots";
+const OBJECTS_DIR: &str = "objects";
+const INDEX_FILENAME: &str = "index.json";
+const SNAPSHOT_SCHEMA: &str = "barqly.vault.metadata-snapshots/1";
+const PRIVATE_KEY_MARKER: &str = "AGE-SECRET-KEY-";
+
+/// Newest snapshots always kept
+pub const KEEP_RECENT: usize = 20;
+
+/// How far back one snapshot per week is kept
+pub const KEEP_WEEKLY_FOR_DAYS: i64 = 91;
+
+/// Serializes index updates between concurrent captures
+static INDEX_LOCK: Mutex<()> = Mutex::new(());
+
+/// Which metadata file a snapshot entry holds
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, specta::Type)]
+#[serde(rename_all = "snake_case")]
+pub enum SnapshotFileKind {
+    Registry,
+    ArchiveIndex,
+    Manifest,
+}
+
+/// A metadata file to capture
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SnapshotSource {
+    pub kind: SnapshotFileKind,
+    pub path: PathBuf,
+}
+
+/// A captured file, stored under its hash
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnapshotFile {
+    pub kind: SnapshotFileKind,
+    pub path: PathBuf,
+    pub sha256: String,
+    pub size: u64,
+}
+
+/// One restore point
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnapshotRecord {
+    pub id: String,
+    pub created_at: DateTime<Utc>,
+    /// Operation about to run when the snapshot was taken
+    pub operation: String,
+    pub files: Vec<SnapshotFile>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SnapshotIndex {
+    schema: String,
+    /// Oldest first
+    #[serde(default)]
+    snapshots: Vec<SnapshotRecord>,
+}
+
+impl Default for SnapshotIndex {
+    fn default() -> Self {
+        Self {
+            schema: SNAPSHOT_SCHEMA.to_string(),
+            snapshots: Vec::new(),
+        }
+    }
+}
+
+/// Content-addressed store of metadata snapshots
+#[derive(Debug, Clone)]
+pub struct MetadataSnapshotStore {
+    root: PathBuf,
+}
+
+impl MetadataSnapshotStore {
+    /// Open the store in the app data directory
+    pub fn open() -> Result<Self, StorageError> {
+        Ok(Self::at(get_app_dir()?.join(SNAPSHOTS_DIR)))
+    }
+
+    /// Open a store rooted at a specific directory
+    pub fn at(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Capture `sources` as a new snapshot, then apply retention
+    ///
+    /// Missing sources are skipped. Fails without writing anything if a
+    /// source looks like private key material.
+    pub fn capture(
+        &self,
+        operation: &str,
+        sources: &[SnapshotSource],
+        now: DateTime<Utc>,
+    ) -> SnapshotResult<SnapshotRecord> {
+        let mut captured = Vec::new();
+        for source in sources {
+            let contents = match fs::read(&source.path) {
+                Ok(contents) => contents,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+                Err(e) => return Err(e.into()),
+            };
+            if is_private_key_file(&source.path) || contains_private_key(&contents) {
+                return Err(format!(
+                    "Refusing to snapshot '{}': it holds private key material",
+                    source.path.display()
+                )
+                .into());
+            }
+            captured.push((source, contents));
+        }
+
+        let _guard = INDEX_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let mut files = Vec::with_capacity(captured.len());
+        for (source, contents) in captured {
+            let sha256 = hex::encode(Sha256::digest(&contents));
+            self.write_object(&sha256, &contents)?;
+            files.push(SnapshotFile {
+                kind: source.kind,
+                path: source.path.clone(),
+                sha256,
+                size: contents.len() as u64,
+            });
+        }
+
+        let record = SnapshotRecord {
+            id: uuid::Uuid::new_v4().to_string(),
+            created_at: now,
+            operation: operation.to_string(),
+            files,
+        };
+        let mut index = self.load_index()?;
+        index.snapshots.push(record.clone());
+        self.prune(&mut index, now)?;
+
+        debug!(
+            snapshot_id = %record.id,
+            operation,
+            files = record.files.len(),
+            "Captured metadata snapshot"
+        );
+        Ok(record)
+    }
+
+    /// Snapshots, newest first
+    pub fn list(&self) -> SnapshotResult<Vec<SnapshotRecord>> {
+        let mut snapshots = self.load_index()?.snapshots;
+        snapshots.reverse();
+        Ok(snapshots)
+    }
+
+    pub fn get(&self, snapshot_id: &str) -> SnapshotResult<Option<SnapshotRecord>> {
+        Ok(self
+            .load_index()?
+            .snapshots
+            .into_iter()
+            .find(|snapshot| snapshot.id == snapshot_id))
+    }
+
+    /// Contents of a captured file, checked against its hash
+    pub fn read_file(&self, file: &SnapshotFile) -> SnapshotResult<Vec<u8>> {
+        let compressed = fs::read(self.object_path(&file.sha256))?;
+        let mut contents = Vec::new();
+        GzDecoder::new(&compressed[..]).read_to_end(&mut contents)?;
+        if hex::encode(Sha256::digest(&contents)) != file.sha256 {
+            return Err(format!("Snapshot object for '{}' is corrupt", file.path.display()).into());
+        }
+        Ok(contents)
+    }
+
+    /// Snapshots, oldest first, with the bytes on disk only they reference
+    ///
+    /// That is the space removing one of them on its own would free.
+    pub fn exclusive_sizes(&self) -> SnapshotResult<Vec<(SnapshotRecord, u64)>> {
+        let snapshots = self.load_index()?.snapshots;
+        let mut references: HashMap<&str, usize> = HashMap::new();
+        for snapshot in &snapshots {
+            let hashes: HashSet<&str> = snapshot.files.iter().map(|f| f.sha256.as_str()).collect();
+            for hash in hashes {
+                *references.entry(hash).or_default() += 1;
+            }
+        }
+
+        let sizes = snapshots
+            .iter()
+            .map(|snapshot| {
+                let hashes: HashSet<&str> =
+                    snapshot.files.iter().map(|f| f.sha256.as_str()).collect();
+                hashes
+                    .into_iter()
+                    .filter(|hash| references.get(hash) == Some(&1))
+                    .filter_map(|hash| fs::metadata(self.object_path(hash)).ok())
+                    .map(|metadata| metadata.len())
+                    .sum()
+            })
+            .collect::<Vec<u64>>();
+        Ok(snapshots.into_iter().zip(sizes).collect())
+    }
+
+    /// Delete snapshots and the objects nothing else references
+    ///
+    /// Returns the bytes freed.
+    pub fn remove(&self, snapshot_ids: &[String]) -> SnapshotResult<u64> {
+        let _guard = INDEX_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let mut index = self.load_index()?;
+        let before = index.snapshots.len();
+        index
+            .snapshots
+            .retain(|snapshot| !snapshot_ids.contains(&snapshot.id));
+        if index.snapshots.len() == before {
+            return Ok(0);
+        }
+        self.save_index(&index)?;
+        Ok(self.remove_unreferenced_objects(&index))
+    }
+
+    /// Drop snapshots outside the retention policy and their unused objects
+    fn prune(&self, index: &mut SnapshotIndex, now: DateTime<Utc>) -> SnapshotResult<()> {
+        let keep = retained_ids(&index.snapshots, now);
+        let before = index.snapshots.len();
+        index
+            .snapshots
+            .retain(|snapshot| keep.contains(&snapshot.id));
+        self.save_index(index)?;
+
+        if index.snapshots.len() < before {
+            self.remove_unreferenced_objects(index);
+            debug!(
+                pruned = before - index.snapshots.len(),
+                "Pruned metadata snapshots"
+            );
+        }
+        Ok(())
+    }
+
+    /// Remove objects no snapshot in `index` references, returning the bytes freed
+    fn remove_unreferenced_objects(&self, index: &SnapshotIndex) -> u64 {
+        let referenced: HashSet<&str> = index
+            .snapshots
+            .iter()
+            .flat_map(|snapshot| snapshot.files.iter().map(|f| f.sha256.as_str()))
+            .collect();
+        let objects = fs::read_dir(self.root.join(OBJECTS_DIR))
+            .into_iter()
+            .flatten();
+        let mut freed = 0;
+        for entry in objects.filter_map(|e| e.ok()) {
+            let name = entry.file_name().to_string_lossy().to_string();
+            let hash = name.trim_end_matches(".gz");
+            if referenced.contains(hash) {
+                continue;
+            }
+            let size = entry.metadata().map(|m| m.len()).unwrap_or(0);
+            match fs::remove_file(entry.path()) {
+                Ok(()) => freed += size,
+                Err(e) => {
+                    warn!(object = %name, error = %e, "Failed to remove unused snapshot object")
+                }
+            }
+        }
+        freed
+    }
+
+    fn write_object(&self, sha256: &str, contents: &[u8]) -> SnapshotResult<()> {
+        let path = self.object_path(sha256);
+        if path.exists() {
+            return Ok(());
+        }
+        fs::create_dir_all(path.parent().unwrap_or(&self.root))?;
+
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(contents)?;
+        atomic_write_sync(&path, &encoder.finish()?)?;
+        Ok(())
+    }
+
+    fn object_path(&self, sha256: &str) -> PathBuf {
+        self.root.join(OBJECTS_DIR).join(format!("{sha256}.gz"))
+    }
+
+    fn load_index(&self) -> SnapshotResult<SnapshotIndex> {
+        let path = self.root.join(INDEX_FILENAME);
+        if !path.exists() {
+            return Ok(SnapshotIndex::default());
+        }
+        Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+    }
+
+    fn save_index(&self, index: &SnapshotIndex) -> SnapshotResult<()> {
+        fs::create_dir_all(&self.root)?;
+        let json = serde_json::to_string_pretty(index)?;
+        atomic_write_sync(&self.root.join(INDEX_FILENAME), json.as_bytes())?;
+        Ok(())
+    }
+}
+
+/// IDs kept by the retention policy
+///
+/// The newest `KEEP_RECENT` are kept, plus the newest snapshot of each
+/// 7-day period counted back from `now`, for `KEEP_WEEKLY_FOR_DAYS`.
+pub fn retained_ids(snapshots: &[SnapshotRecord], now: DateTime<Utc>) -> HashSet<String> {
+    let mut newest_first: Vec<&SnapshotRecord> = snapshots.iter().collect();
+    newest_first.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+
+    let mut keep: HashSet<String> = newest_first
+        .iter()
+        .take(KEEP_RECENT)
+        .map(|snapshot| snapshot.id.clone())
+        .collect();
+
+    let mut weeks_seen = HashSet::new();
+    for snapshot in newest_first {
+        let age_days = (now - snapshot.created_at).num_days();
+        if age_days < KEEP_WEEKLY_FOR_DAYS && weeks_seen.insert(age_days.div_euclid(7)) {
+            keep.insert(snapshot.id.clone());
+        }
+    }
+    keep
+}
+
+/// Where the metadata files live
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SnapshotLayout {
+    pub registry: PathBuf,
+    pub archive_index: PathBuf,
+    pub manifests_dir: PathBuf,
+}
+
+impl SnapshotLayout {
+    /// Locations used by this device
+    pub fn current() -> SnapshotResult<Self> {
+        Ok(Self {
+            registry: KeyRegistry::get_registry_path()?,
+            archive_index: ArchiveIndex::get_index_path()?,
+            manifests_dir: get_vaults_manifest_dir()?,
+        })
+    }
+
+    /// The registry, the archive index and every vault manifest
+    pub fn sources(&self) -> SnapshotResult<Vec<SnapshotSource>> {
+        let mut sources = vec![
+            SnapshotSource {
+                kind: SnapshotFileKind::Registry,
+                path: self.registry.clone(),
+            },
+            SnapshotSource {
+                kind: SnapshotFileKind::ArchiveIndex,
+                path: self.archive_index.clone(),
+            },
+        ];
+
+        let mut manifests: Vec<PathBuf> = fs::read_dir(&self.manifests_dir)?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| is_manifest_file(path))
+            .collect();
+        manifests.sort();
+        sources.extend(manifests.into_iter().map(|path| SnapshotSource {
+            kind: SnapshotFileKind::Manifest,
+            path,
+        }));
+        Ok(sources)
+    }
+
+    /// A captured file may be written back to its recorded path
+    ///
+    /// Guards restores against an index edited to point elsewhere.
+    pub fn allows(&self, file: &SnapshotFile) -> bool {
+        match file.kind {
+            SnapshotFileKind::Registry => file.path == self.registry,
+            SnapshotFileKind::ArchiveIndex => file.path == self.archive_index,
+            SnapshotFileKind::Manifest => {
+                file.path.parent() == Some(self.manifests_dir.as_path())
+                    && is_manifest_file(&file.path)
+            }
+        }
+    }
+}
+
+/// Take a restore point before `operation`, logging rather than failing
+///
+/// Returns the snapshot ID when one was taken.
+pub fn snapshot_before(operation: &str) -> Option<String> {
+    let result = SnapshotLayout::current()
+        .and_then(|layout| layout.sources())
+        .and_then(|sources| {
+            MetadataSnapshotStore::open()?.capture(operation, &sources, Utc::now())
+        });
+    match result {
+        Ok(record) => Some(record.id),
+        Err(e) => {
+            warn!(operation, error = %e, "Failed to capture metadata snapshot");
+            None
+        }
+    }
+}
+
+/// A vault manifest file (`<name>.manifest`, not a temp or backup copy)
+pub fn is_manifest_file(path: &Path) -> bool {
+    path.extension().and_then(|ext| ext.to_str()) == Some("manifest")
+        && path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .is_some_and(|stem| !stem.ends_with(".tmp") && !stem.ends_with(".bak"))
+}
+
+fn is_private_key_file(path: &Path) -> bool {
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    name.ends_with(".agekey") || name.ends_with(".agekey.enc")
+}
+
+fn contains_private_key(contents: &[u8]) -> bool {
+    String::from_utf8_lossy(contents)
+        .to_uppercase()
+        .contains(PRIVATE_KEY_MARKER)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+    use tempfile::TempDir;
+
+    fn record(id: &str, created_at: DateTime<Utc>) -> SnapshotRecord {
+        SnapshotRecord {
+            id: id.to_string(),
+            created_at,
+            operation: "test".to_string(),
+            files: Vec::new(),
+        }
+    }
+
+    fn source(kind: SnapshotFileKind, path: &Path) -> SnapshotSource {
+        SnapshotSource {
+            kind,
+            path: path.to_path_buf(),
+        }
+    }
+
+    #[test]
+    fn test_capture_deduplicates_unchanged_files() {
+        let temp = TempDir::new().unwrap();
+        let registry = temp.path().join("registry.json");
+        let manifest = temp.path().join("Family.manifest");
+        fs::write(&registry, b"{\"keys\":1}").unwrap();
+        fs::write(&manifest, b"{\"vault\":1}").unwrap();
+        let store = MetadataSnapshotStore::at(temp.path().join("snapshots"));
+        let sources = [
+            source(SnapshotFileKind::Registry, &registry),
+            source(SnapshotFileKind::Manifest, &manifest),
+            source(
+                SnapshotFileKind::ArchiveIndex,
+                &temp.path().join("missing.json"),
+            ),
+        ];
+
+        let first = store
+            .capture("attach_key_to_vault", &sources, Utc::now())
+            .unwrap();
+        fs::write(&registry, b"{\"keys\":2}").unwrap();
+        let second = store
+            .capture("update_key_label", &sources, Utc::now())
+            .unwrap();
+
+        assert_eq!(first.files.len(), 2);
+        assert_eq!(first.files[1].sha256, second.files[1].sha256);
+        let objects = fs::read_dir(temp.path().join("snapshots/objects")).unwrap();
+        assert_eq!(objects.count(), 3);
+
+        assert_eq!(store.read_file(&first.files[0]).unwrap(), b"{\"keys\":1}");
+        let listed = store.list().unwrap();
+        assert_eq!(listed[0].id, second.id);
+        assert_eq!(listed[1].operation, "attach_key_to_vault");
+    }
+
+    #[test]
+    fn test_capture_refuses_private_key_material() {
+        let temp = TempDir::new().unwrap();
+        let key_file = temp.path().join("mykey.agekey.enc");
+        let leaky = temp.path().join("registry.json");
+        fs::write(&key_file, b"encrypted").unwrap();
+        fs::write(&leaky, b"{\"note\":\"age-secret-key-1qqq\"}").unwrap();
+        let store = MetadataSnapshotStore::at(temp.path().join("snapshots"));
+
+        for path in [&key_file, &leaky] {
+            let sources = [source(SnapshotFileKind::Registry, path)];
+            assert!(store.capture("test", &sources, Utc::now()).is_err());
+        }
+        assert!(store.list().unwrap().is_empty());
+        assert!(!temp.path().join("snapshots/objects").exists());
+    }
+
+    #[test]
+    fn test_retention_keeps_recent_and_weekly() {
+        let now = Utc::now();
+        // 30 snapshots a day apart, then 10 more beyond the weekly window
+        let mut snapshots: Vec<SnapshotRecord> = (0..30)
+            .map(|day| record(&format!("day-{day}"), now - Duration::days(day)))
+            .collect();
+        snapshots
+            .extend((0..10).map(|i| record(&format!("old-{i}"), now - Duration::days(120 + i))));
+
+        let keep = retained_ids(&snapshots, now);
+
+        // Newest 20 (days 0..=19), plus the newest of weeks 3 and 4 (days 21 and 28)
+        for day in 0..20 {
+            assert!(keep.contains(&format!("day-{day}")));
+        }
+        assert!(keep.contains("day-21"));
+        assert!(keep.contains("day-28"));
+        assert!(!keep.contains("day-20"));
+        assert!(!keep.contains("day-29"));
+        assert!(!keep.iter().any(|id| id.starts_with("old-")));
+        assert_eq!(keep.len(), 22);
+    }
+
+    #[test]
+    fn test_prune_removes_unreferenced_objects() {
+        let temp = TempDir::new().unwrap();
+        let registry = temp.path().join("registry.json");
+        let store = MetadataSnapshotStore::at(temp.path().join("snapshots"));
+        let sources = [source(SnapshotFileKind::Registry, &registry)];
+        let now = Utc::now();
+
+        // An old snapshot with contents no later snapshot shares
+        fs::write(&registry, b"ancient").unwrap();
+        store
+            .capture("old", &sources, now - Duration::days(200))
+            .unwrap();
+        for i in 0..KEEP_RECENT {
+            fs::write(&registry, format!("v{i}")).unwrap();
+            store.capture("recent", &sources, now).unwrap();
+        }
+
+        let snapshots = store.list().unwrap();
+        assert_eq!(snapshots.len(), KEEP_RECENT);
+        assert!(snapshots.iter().all(|s| s.operation == "recent"));
+        let objects = fs::read_dir(temp.path().join("snapshots/objects")).unwrap();
+        assert_eq!(objects.count(), KEEP_RECENT);
+    }
+}
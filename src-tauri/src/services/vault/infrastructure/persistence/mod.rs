@@ -2,13 +2,55 @@
 //!
 //! Handles vault metadata storage using JSON file persistence.
 
+pub mod app_config;
+pub mod archive_index;
+pub mod file_search_index;
+pub mod launch_diagnostics;
+pub mod maintenance_history;
+pub mod manifest_signing;
 pub mod metadata;
+pub mod metadata_snapshots;
+pub mod onboarding_progress;
+pub mod operation_log;
+pub mod output_index;
+pub mod statistics_history;
+pub mod vault_generation;
+pub mod vault_order;
 pub mod vault_persistence;
+pub mod vault_settings;
 
 // Re-export main vault operations
 pub use vault_persistence::{
-    delete_vault, get_current_vault, get_vault, list_vaults, load_vault, save_vault, vault_exists,
+    delete_vault, find_vault_sync, get_current_vault, get_vault, list_vaults, load_vault,
+    save_vault, save_vault_sync, vault_exists, vault_exists_sync, vault_pending_write,
 };
 
+pub use app_config::{AppConfig, configured_locale};
+pub use archive_index::ArchiveIndex;
+pub use file_search_index::FileSearchIndex;
+pub use launch_diagnostics::LaunchDiagnosticsStore;
+pub use maintenance_history::MaintenanceHistory;
+pub use manifest_signing::{
+    ManifestSignature, ManifestSignatureCheck, ManifestSigner, SignatureStatus,
+    signed_manifest_json,
+};
+pub use metadata_snapshots::{
+    MetadataSnapshotStore, SnapshotFile, SnapshotFileKind, SnapshotLayout, SnapshotRecord,
+    SnapshotSource, snapshot_before,
+};
+pub use onboarding_progress::OnboardingProgress;
+pub use operation_log::{
+    APP_LOG_SCOPE, ChainBreak, ChainBreakKind, LogTruncation, LoggedOperation, OperationLog,
+    OperationLogEntry, OperationLogVerification,
+};
+pub use output_index::{OutputClaim, OutputIndex};
+pub use statistics_history::StatisticsHistory;
+pub use vault_generation::{
+    GenerationLedger, VaultConflictError, conflicted_copies, conflicted_manifest_copies,
+    is_conflicted_copy, stamp_manifest,
+};
+pub use vault_order::{OrderMismatch, VaultOrder, VaultOrderEntry};
+pub use vault_settings::{NotificationSnooze, VaultSettings, VaultSettingsRegistry};
+
 // Re-export metadata types
 pub use metadata::{MetadataStorage, RecipientInfo, RecipientType, VaultMetadata};
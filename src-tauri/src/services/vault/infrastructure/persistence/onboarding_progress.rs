@@ -0,0 +1,96 @@
+//! Onboarding progress
+//!
+//! Caches when each onboarding milestone was first seen complete. Completion
+//! itself is derived from vaults, keys and archives; this file only keeps the
+//! timestamps so milestones don't revert. Stored as a single JSON file in the
+//! config directory.
+
+use crate::services::shared::infrastructure::io::atomic_write_sync;
+use crate::services::shared::infrastructure::path_management::get_config_dir;
+use crate::services::vault::domain::models::OnboardingMilestone;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tracing::debug;
+
+const PROGRESS_FILENAME: &str = "onboarding_progress.json";
+const PROGRESS_SCHEMA: &str = "barqly.vault.onboarding-progress/1";
+
+/// Milestones completed on this device
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OnboardingProgress {
+    pub schema: String,
+    #[serde(default)]
+    pub completed: BTreeMap<OnboardingMilestone, DateTime<Utc>>,
+}
+
+impl Default for OnboardingProgress {
+    fn default() -> Self {
+        Self {
+            schema: PROGRESS_SCHEMA.to_string(),
+            completed: BTreeMap::new(),
+        }
+    }
+}
+
+impl OnboardingProgress {
+    fn get_progress_path() -> Result<PathBuf, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(get_config_dir()?.join(PROGRESS_FILENAME))
+    }
+
+    /// Load progress from the config directory (empty if none saved yet)
+    pub fn load() -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        Self::load_from(&Self::get_progress_path()?)
+    }
+
+    /// Save progress to the config directory
+    pub fn save(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.save_to(&Self::get_progress_path()?)
+    }
+
+    pub fn load_from(path: &Path) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        if !path.exists() {
+            debug!("Onboarding progress doesn't exist, starting empty");
+            return Ok(Self::default());
+        }
+
+        let content = fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&content)?)
+    }
+
+    pub fn save_to(&self, path: &Path) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let json = serde_json::to_string_pretty(self)?;
+        atomic_write_sync(path, json.as_bytes())?;
+        debug!(
+            completed = self.completed.len(),
+            "Saved onboarding progress"
+        );
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_progress_round_trip() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join(PROGRESS_FILENAME);
+        assert_eq!(
+            OnboardingProgress::load_from(&path).unwrap(),
+            OnboardingProgress::default()
+        );
+
+        let mut progress = OnboardingProgress::default();
+        progress
+            .completed
+            .insert(OnboardingMilestone::VaultCreated, Utc::now());
+        progress.save_to(&path).unwrap();
+
+        assert_eq!(OnboardingProgress::load_from(&path).unwrap(), progress);
+    }
+}
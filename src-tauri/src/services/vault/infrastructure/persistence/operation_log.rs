@@ -0,0 +1,615 @@
+//! Operation log
+//!
+//! A local record of operations that hand vault information to something
+//! outside the app (e.g. inventory exports, post-operation hooks, clipboard
+//! copies), so the user can see later what left the device and where it
+//! went. Stored as a single JSON file in the config directory, capped at
+//! `MAX_LOG_ENTRIES` with the oldest dropped.
+//!
+//! Each vault's entries form a hash chain: every entry carries the SHA-256 of
+//! the vault's previous entry in canonical form, so editing or removing an
+//! entry breaks the link after it. Dropping old entries for the cap appends a
+//! truncation record naming the last dropped hash, which re-anchors the chain
+//! instead of silently breaking it. The newest hash (the head) is mirrored in
+//! the vault's manifest so cutting entries off the end is caught too.
+
+use crate::services::shared::infrastructure::io::atomic_write_sync;
+use crate::services::shared::infrastructure::path_management::get_config_dir;
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tracing::{debug, info};
+
+const LOG_FILENAME: &str = "operation_log.json";
+const LOG_SCHEMA: &str = "barqly.vault.operation-log/2";
+
+/// Written before entries were chained; chained on load
+const LOG_SCHEMA_V1: &str = "barqly.vault.operation-log/1";
+
+/// Entries kept before the oldest are dropped
+pub const MAX_LOG_ENTRIES: usize = 500;
+
+/// Stands in for the vault ID on entries about the whole app
+pub const APP_LOG_SCOPE: &str = "app";
+
+/// Kind of operation recorded
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+#[serde(rename_all = "snake_case")]
+pub enum LoggedOperation {
+    InventoryExport,
+    /// A post-operation hook ran (or was tested)
+    HookRun,
+    /// Older entries were dropped to keep the log within its cap
+    LogTruncated,
+    /// Cached sensitive state was wiped on request
+    PanicLock,
+    /// A value was copied to the clipboard
+    ClipboardCopy,
+}
+
+impl LoggedOperation {
+    /// Name used in the canonical form; fixed even if serde names change
+    fn canonical_name(self) -> &'static str {
+        match self {
+            Self::InventoryExport => "inventory_export",
+            Self::HookRun => "hook_run",
+            Self::LogTruncated => "log_truncated",
+            Self::PanicLock => "panic_lock",
+            Self::ClipboardCopy => "clipboard_copy",
+        }
+    }
+}
+
+/// Where a vault's chain continues after its oldest entries were dropped
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+pub struct LogTruncation {
+    pub dropped_entries: u32,
+    /// Hash of the last dropped entry, which the oldest kept entry links to
+    pub anchor_hash: String,
+}
+
+/// One recorded operation
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+pub struct OperationLogEntry {
+    pub at: DateTime<Utc>,
+    pub operation: LoggedOperation,
+    pub vault_id: String,
+    /// What was done, without file names from the vault
+    pub summary: String,
+    /// Hash of the vault's previous entry; set when appended, None for the first
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prev_hash: Option<String>,
+    /// Set on truncation records only
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub truncation: Option<LogTruncation>,
+}
+
+impl OperationLogEntry {
+    /// Entry for an operation happening now
+    pub fn new(operation: LoggedOperation, vault_id: &str, summary: String) -> Self {
+        Self {
+            at: Utc::now(),
+            operation,
+            vault_id: vault_id.to_string(),
+            summary,
+            prev_hash: None,
+            truncation: None,
+        }
+    }
+
+    /// Bytes the chain hashes
+    ///
+    /// One `name=length:value` line per field in a fixed order, with absent
+    /// optional fields left out. Lengths are in bytes, so no value can run into
+    /// the next field. Timestamps always carry nine fractional digits and
+    /// numbers are plain decimal integers, so the bytes don't depend on how
+    /// the JSON file happened to format them.
+    pub fn canonical_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        let mut field = |name: &str, value: &str| {
+            out.extend_from_slice(format!("{}={}:", name, value.len()).as_bytes());
+            out.extend_from_slice(value.as_bytes());
+            out.push(b'\n');
+        };
+
+        field("schema", LOG_SCHEMA);
+        field("at", &self.at.to_rfc3339_opts(SecondsFormat::Nanos, true));
+        field("operation", self.operation.canonical_name());
+        field("vault_id", &self.vault_id);
+        field("summary", &self.summary);
+        if let Some(prev_hash) = &self.prev_hash {
+            field("prev_hash", prev_hash);
+        }
+        if let Some(truncation) = &self.truncation {
+            field("dropped_entries", &truncation.dropped_entries.to_string());
+            field("anchor_hash", &truncation.anchor_hash);
+        }
+        out
+    }
+
+    /// SHA-256 of the canonical bytes, lowercase hex
+    pub fn hash(&self) -> String {
+        hex::encode(Sha256::digest(self.canonical_bytes()))
+    }
+}
+
+/// Which link of the chain failed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, specta::Type)]
+#[serde(rename_all = "snake_case")]
+pub enum ChainBreakKind {
+    /// An entry doesn't link to the one before it (or to the truncation anchor)
+    PreviousHash,
+    /// The newest entry isn't the one the manifest recorded
+    ManifestHead,
+}
+
+/// First place a vault's chain doesn't hold
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, specta::Type)]
+pub struct ChainBreak {
+    pub kind: ChainBreakKind,
+    /// Position in the vault's entries, oldest first; the entry count for a
+    /// manifest head mismatch
+    pub entry_index: usize,
+    pub expected_hash: Option<String>,
+    pub actual_hash: Option<String>,
+}
+
+/// Result of walking a vault's chain
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, specta::Type)]
+pub struct OperationLogVerification {
+    pub vault_id: String,
+    /// Entries whose link held before the first break (all when intact)
+    pub entries_checked: usize,
+    /// Hash of the vault's newest entry
+    pub head_hash: Option<String>,
+    /// Head recorded in the manifest; not compared when absent
+    pub manifest_head: Option<String>,
+    pub intact: bool,
+    pub first_break: Option<ChainBreak>,
+}
+
+/// Operations recorded on this device, oldest first
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OperationLog {
+    pub schema: String,
+    #[serde(default)]
+    pub entries: Vec<OperationLogEntry>,
+}
+
+impl Default for OperationLog {
+    fn default() -> Self {
+        Self {
+            schema: LOG_SCHEMA.to_string(),
+            entries: Vec::new(),
+        }
+    }
+}
+
+impl OperationLog {
+    fn get_log_path() -> Result<PathBuf, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(get_config_dir()?.join(LOG_FILENAME))
+    }
+
+    /// Append an entry to the log in the config directory
+    ///
+    /// Returns the new head of every vault whose chain changed.
+    pub fn record(
+        entry: OperationLogEntry,
+    ) -> Result<BTreeMap<String, String>, Box<dyn std::error::Error + Send + Sync>> {
+        Self::record_in(&Self::get_log_path()?, entry)
+    }
+
+    pub fn record_in(
+        path: &Path,
+        entry: OperationLogEntry,
+    ) -> Result<BTreeMap<String, String>, Box<dyn std::error::Error + Send + Sync>> {
+        let mut log = Self::load_from(path)?;
+        let heads = log.push(entry);
+        log.save_to(path)?;
+        Ok(heads)
+    }
+
+    /// Hash of a vault's newest entry
+    pub fn head(&self, vault_id: &str) -> Option<String> {
+        self.entries
+            .iter()
+            .rev()
+            .find(|entry| entry.vault_id == vault_id)
+            .map(OperationLogEntry::hash)
+    }
+
+    /// Link an entry into its vault's chain and append it, dropping the oldest
+    /// past `MAX_LOG_ENTRIES`
+    ///
+    /// Returns the new head of every vault whose chain changed.
+    pub fn push(&mut self, mut entry: OperationLogEntry) -> BTreeMap<String, String> {
+        entry.prev_hash = self.head(&entry.vault_id);
+        let mut heads = BTreeMap::from([(entry.vault_id.clone(), entry.hash())]);
+        let at = entry.at;
+        self.entries.push(entry);
+        self.enforce_retention(at, &mut heads);
+        heads
+    }
+
+    /// Drop the oldest entries past the cap, re-anchoring each affected chain
+    ///
+    /// Every vault that loses entries gets a truncation record, which takes
+    /// room too, so enough is dropped to fit those as well.
+    fn enforce_retention(&mut self, at: DateTime<Utc>, heads: &mut BTreeMap<String, String>) {
+        let len = self.entries.len();
+        if len <= MAX_LOG_ENTRIES {
+            return;
+        }
+        let mut drop = len - MAX_LOG_ENTRIES;
+        loop {
+            let needed = (len - MAX_LOG_ENTRIES + vault_count(&self.entries[..drop])).min(len);
+            if needed <= drop {
+                break;
+            }
+            drop = needed;
+        }
+
+        // Last dropped hash and dropped count per vault, in order of appearance
+        let mut truncated: Vec<(String, String, u32)> = Vec::new();
+        for entry in &self.entries[..drop] {
+            let hash = entry.hash();
+            match truncated
+                .iter_mut()
+                .find(|(id, _, _)| *id == entry.vault_id)
+            {
+                Some((_, anchor, count)) => {
+                    *anchor = hash;
+                    *count += 1;
+                }
+                None => truncated.push((entry.vault_id.clone(), hash, 1)),
+            }
+        }
+        self.entries.drain(..drop);
+
+        for (vault_id, anchor_hash, dropped_entries) in truncated {
+            // A vault with nothing left links its record to the anchor itself
+            let prev_hash = self.head(&vault_id).unwrap_or_else(|| anchor_hash.clone());
+            let record = OperationLogEntry {
+                at,
+                operation: LoggedOperation::LogTruncated,
+                vault_id: vault_id.clone(),
+                summary: format!(
+                    "Dropped the {} oldest entries to keep the log within {} entries",
+                    dropped_entries, MAX_LOG_ENTRIES
+                ),
+                prev_hash: Some(prev_hash),
+                truncation: Some(LogTruncation {
+                    dropped_entries,
+                    anchor_hash,
+                }),
+            };
+            heads.insert(vault_id, record.hash());
+            self.entries.push(record);
+        }
+        info!(dropped = drop, "Truncated operation log");
+    }
+
+    /// Walk a vault's chain from its anchor to its head
+    ///
+    /// The oldest entry links to nothing, or to the anchor of the newest
+    /// truncation record. `manifest_head`, when given, must be the head.
+    pub fn verify(&self, vault_id: &str, manifest_head: Option<&str>) -> OperationLogVerification {
+        let chain: Vec<&OperationLogEntry> = self
+            .entries
+            .iter()
+            .filter(|entry| entry.vault_id == vault_id)
+            .collect();
+        let mut expected = chain
+            .iter()
+            .rev()
+            .find_map(|entry| entry.truncation.as_ref())
+            .map(|truncation| truncation.anchor_hash.clone());
+
+        let mut first_break = None;
+        let mut entries_checked = 0;
+        for (index, entry) in chain.iter().enumerate() {
+            if entry.prev_hash != expected {
+                first_break = Some(ChainBreak {
+                    kind: ChainBreakKind::PreviousHash,
+                    entry_index: index,
+                    expected_hash: expected,
+                    actual_hash: entry.prev_hash.clone(),
+                });
+                break;
+            }
+            expected = Some(entry.hash());
+            entries_checked += 1;
+        }
+
+        let head_hash = chain.last().map(|entry| entry.hash());
+        if first_break.is_none()
+            && let Some(mirrored) = manifest_head
+            && head_hash.as_deref() != Some(mirrored)
+        {
+            first_break = Some(ChainBreak {
+                kind: ChainBreakKind::ManifestHead,
+                entry_index: chain.len(),
+                expected_hash: Some(mirrored.to_string()),
+                actual_hash: head_hash.clone(),
+            });
+        }
+
+        OperationLogVerification {
+            vault_id: vault_id.to_string(),
+            entries_checked,
+            head_hash,
+            manifest_head: manifest_head.map(str::to_string),
+            intact: first_break.is_none(),
+            first_break,
+        }
+    }
+
+    /// Load the log from the config directory (empty if none saved yet)
+    pub fn load() -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        Self::load_from(&Self::get_log_path()?)
+    }
+
+    pub fn load_from(path: &Path) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        if !path.exists() {
+            debug!("Operation log doesn't exist, starting empty");
+            return Ok(Self::default());
+        }
+
+        let content = fs::read_to_string(path)?;
+        let mut log: Self = serde_json::from_str(&content)?;
+        if log.schema == LOG_SCHEMA_V1 {
+            log.chain_legacy_entries();
+        }
+        Ok(log)
+    }
+
+    /// Chain entries written before the log was chained, as they stand now
+    fn chain_legacy_entries(&mut self) {
+        let entries = std::mem::take(&mut self.entries);
+        for mut entry in entries {
+            entry.prev_hash = self.head(&entry.vault_id);
+            self.entries.push(entry);
+        }
+        self.schema = LOG_SCHEMA.to_string();
+        debug!(entries = self.entries.len(), "Chained legacy operation log");
+    }
+
+    pub fn save_to(&self, path: &Path) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let json = serde_json::to_string_pretty(self)?;
+        atomic_write_sync(path, json.as_bytes())?;
+        debug!(entries = self.entries.len(), "Saved operation log");
+        Ok(())
+    }
+}
+
+/// Distinct vaults among `entries`
+fn vault_count(entries: &[OperationLogEntry]) -> usize {
+    let mut vaults: Vec<&str> = entries.iter().map(|e| e.vault_id.as_str()).collect();
+    vaults.sort_unstable();
+    vaults.dedup();
+    vaults.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn entry(summary: &str) -> OperationLogEntry {
+        entry_for("vault-001", summary)
+    }
+
+    fn entry_for(vault_id: &str, summary: &str) -> OperationLogEntry {
+        OperationLogEntry::new(
+            LoggedOperation::InventoryExport,
+            vault_id,
+            summary.to_string(),
+        )
+    }
+
+    #[test]
+    fn test_record_appends_and_caps_entries() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join(LOG_FILENAME);
+
+        OperationLog::record_in(&path, entry("first")).unwrap();
+        OperationLog::record_in(&path, entry("second")).unwrap();
+        let log = OperationLog::load_from(&path).unwrap();
+        assert_eq!(log.schema, LOG_SCHEMA);
+        assert_eq!(log.entries.len(), 2);
+        assert_eq!(log.entries[1].summary, "second");
+
+        let mut full = OperationLog::default();
+        for i in 0..=MAX_LOG_ENTRIES {
+            full.push(entry(&i.to_string()));
+        }
+        assert_eq!(full.entries.len(), MAX_LOG_ENTRIES);
+        // One entry dropped plus one to make room for the truncation record
+        assert_eq!(full.entries[0].summary, "2");
+        assert_eq!(
+            full.entries.last().unwrap().operation,
+            LoggedOperation::LogTruncated
+        );
+    }
+
+    #[test]
+    fn test_canonical_bytes_are_fixed() {
+        let mut entry = OperationLogEntry {
+            at: "2026-03-01T12:00:00Z".parse().unwrap(),
+            operation: LoggedOperation::HookRun,
+            vault_id: "vault-001".to_string(),
+            summary: "Ran \"backup\" → exit 0".to_string(),
+            prev_hash: None,
+            truncation: None,
+        };
+        assert_eq!(
+            String::from_utf8(entry.canonical_bytes()).unwrap(),
+            "schema=28:barqly.vault.operation-log/2\n\
+             at=30:2026-03-01T12:00:00.000000000Z\n\
+             operation=8:hook_run\n\
+             vault_id=9:vault-001\n\
+             summary=23:Ran \"backup\" → exit 0\n"
+        );
+
+        entry.prev_hash = Some("ab".repeat(32));
+        entry.truncation = Some(LogTruncation {
+            dropped_entries: 7,
+            anchor_hash: "cd".repeat(32),
+        });
+        let bytes = String::from_utf8(entry.canonical_bytes()).unwrap();
+        assert!(bytes.ends_with(&format!(
+            "prev_hash=64:{}\ndropped_entries=1:7\nanchor_hash=64:{}\n",
+            "ab".repeat(32),
+            "cd".repeat(32)
+        )));
+    }
+
+    #[test]
+    fn test_canonical_bytes_survive_json_round_trip() {
+        let mut entry = entry("round trip");
+        entry.at = "2026-03-01T12:00:00.120Z".parse().unwrap();
+        entry.prev_hash = Some("ab".repeat(32));
+
+        let json = serde_json::to_string(&entry).unwrap();
+        let reloaded: OperationLogEntry = serde_json::from_str(&json).unwrap();
+        assert_eq!(reloaded.canonical_bytes(), entry.canonical_bytes());
+        assert!(
+            String::from_utf8(entry.canonical_bytes())
+                .unwrap()
+                .contains("at=30:2026-03-01T12:00:00.120000000Z\n")
+        );
+    }
+
+    #[test]
+    fn test_canonical_fields_cannot_run_together() {
+        let mut a = entry("x\nvault_id=9:vault-002");
+        let mut b = entry("x");
+        b.at = a.at;
+        a.vault_id = "v".to_string();
+        b.vault_id = "v".to_string();
+        assert_ne!(a.canonical_bytes(), b.canonical_bytes());
+        assert_ne!(a.hash(), b.hash());
+    }
+
+    #[test]
+    fn test_chain_verifies_and_detects_edits() {
+        let mut log = OperationLog::default();
+        for i in 0..5 {
+            log.push(entry(&format!("export {i}")));
+            log.push(entry_for("vault-002", "other vault"));
+        }
+        let head = log.head("vault-001").unwrap();
+
+        let clean = log.verify("vault-001", Some(&head));
+        assert!(clean.intact, "{:?}", clean.first_break);
+        assert_eq!(clean.entries_checked, 5);
+        assert_eq!(clean.head_hash.as_deref(), Some(head.as_str()));
+
+        // Edit the middle entry of vault-001's chain (global index 4)
+        let mut tampered = log.clone();
+        tampered.entries[4].summary = "nothing to see".to_string();
+        let report = tampered.verify("vault-001", Some(&head));
+        let first_break = report.first_break.unwrap();
+        assert_eq!(first_break.kind, ChainBreakKind::PreviousHash);
+        assert_eq!(first_break.entry_index, 3);
+        assert_eq!(first_break.expected_hash, Some(tampered.entries[4].hash()));
+        assert_eq!(first_break.actual_hash, Some(log.entries[4].hash()));
+        assert_eq!(report.entries_checked, 3);
+        // Other vaults' chains are unaffected
+        assert!(tampered.verify("vault-002", None).intact);
+
+        // Dropping the newest entry only shows against the manifest head
+        let mut cut = log.clone();
+        cut.entries.remove(8);
+        assert!(cut.verify("vault-001", None).intact);
+        let report = cut.verify("vault-001", Some(&head));
+        assert_eq!(
+            report.first_break.unwrap().kind,
+            ChainBreakKind::ManifestHead
+        );
+    }
+
+    #[test]
+    fn test_retention_keeps_chain_verifiable() {
+        let mut log = OperationLog::default();
+        let mut heads = BTreeMap::new();
+        for i in 0..MAX_LOG_ENTRIES + 40 {
+            let vault_id = if i % 3 == 0 { "vault-002" } else { "vault-001" };
+            heads.extend(log.push(entry_for(vault_id, &i.to_string())));
+        }
+        assert!(log.entries.len() <= MAX_LOG_ENTRIES);
+
+        for vault_id in ["vault-001", "vault-002"] {
+            let report = log.verify(vault_id, heads.get(vault_id).map(String::as_str));
+            assert!(report.intact, "{vault_id}: {:?}", report.first_break);
+            assert_eq!(report.head_hash.as_ref(), heads.get(vault_id));
+        }
+        let records = log
+            .entries
+            .iter()
+            .filter(|e| e.operation == LoggedOperation::LogTruncated)
+            .count();
+        assert!(records > 0);
+
+        // Removing the oldest kept entry by hand is not a truncation
+        let first = log
+            .entries
+            .iter()
+            .position(|e| e.vault_id == "vault-001")
+            .unwrap();
+        log.entries.remove(first);
+        let report = log.verify("vault-001", heads.get("vault-001").map(String::as_str));
+        assert_eq!(report.first_break.unwrap().entry_index, 0);
+    }
+
+    #[test]
+    fn test_vault_with_all_entries_dropped_stays_anchored() {
+        let mut log = OperationLog::default();
+        let mut heads = log.push(entry_for("vault-old", "only entry"));
+        for i in 0..MAX_LOG_ENTRIES {
+            heads.extend(log.push(entry(&i.to_string())));
+        }
+
+        let old: Vec<_> = log
+            .entries
+            .iter()
+            .filter(|e| e.vault_id == "vault-old")
+            .collect();
+        assert_eq!(old.len(), 1);
+        assert_eq!(old[0].operation, LoggedOperation::LogTruncated);
+        let report = log.verify("vault-old", heads.get("vault-old").map(String::as_str));
+        assert!(report.intact, "{:?}", report.first_break);
+    }
+
+    #[test]
+    fn test_legacy_log_is_chained_on_load() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join(LOG_FILENAME);
+        let legacy = serde_json::json!({
+            "schema": LOG_SCHEMA_V1,
+            "entries": [
+                {
+                    "at": "2026-01-01T00:00:00Z",
+                    "operation": "inventory_export",
+                    "vault_id": "vault-001",
+                    "summary": "first"
+                },
+                {
+                    "at": "2026-01-02T00:00:00Z",
+                    "operation": "hook_run",
+                    "vault_id": "vault-001",
+                    "summary": "second"
+                }
+            ]
+        });
+        fs::write(&path, legacy.to_string()).unwrap();
+
+        let log = OperationLog::load_from(&path).unwrap();
+        assert_eq!(log.schema, LOG_SCHEMA);
+        assert_eq!(log.entries[1].prev_hash, Some(log.entries[0].hash()));
+        assert!(log.verify("vault-001", None).intact);
+    }
+}
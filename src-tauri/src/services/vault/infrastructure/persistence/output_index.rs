@@ -0,0 +1,163 @@
+//! Cross-vault output index
+//!
+//! Which vault wrote each archive in the external folders encryption has
+//! written to, keyed by the folder's canonical path so different spellings
+//! of one folder share an entry. Device-local, stored as a single JSON file
+//! in the config directory.
+//!
+//! Claims for archives deleted outside the app are dropped by `heal` when
+//! their folder is next used. A folder that can't be reached (an unplugged
+//! drive) keeps its claims.
+
+use crate::services::shared::infrastructure::io::atomic_write_sync;
+use crate::services::shared::infrastructure::path_management::get_config_dir;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tracing::debug;
+
+const INDEX_FILENAME: &str = "output_index.json";
+const INDEX_SCHEMA: &str = "barqly.vault.output-index/1";
+
+/// An archive in an output folder and the vault that wrote it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OutputClaim {
+    pub file_name: String,
+    pub vault_id: String,
+    pub vault_name: String,
+    pub recorded_at: DateTime<Utc>,
+}
+
+/// Archive claims per output folder
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OutputIndex {
+    pub schema: String,
+    /// Claims by canonical folder path
+    #[serde(default)]
+    pub directories: HashMap<String, Vec<OutputClaim>>,
+}
+
+impl Default for OutputIndex {
+    fn default() -> Self {
+        Self {
+            schema: INDEX_SCHEMA.to_string(),
+            directories: HashMap::new(),
+        }
+    }
+}
+
+impl OutputIndex {
+    pub fn get_index_path() -> Result<PathBuf, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(get_config_dir()?.join(INDEX_FILENAME))
+    }
+
+    pub fn load_from(path: &Path) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        if !path.exists() {
+            debug!("Output index doesn't exist, starting empty");
+            return Ok(Self::default());
+        }
+
+        let content = fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&content)?)
+    }
+
+    pub fn save_to(&self, path: &Path) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let json = serde_json::to_string(self)?;
+        atomic_write_sync(path, json.as_bytes())?;
+        debug!(
+            directory_count = self.directories.len(),
+            "Saved output index"
+        );
+        Ok(())
+    }
+
+    /// Index key of an output folder
+    pub fn directory_key(dir: &Path) -> std::io::Result<String> {
+        Ok(dir.canonicalize()?.to_string_lossy().into_owned())
+    }
+
+    /// The claim on `file_name` in the folder, if any
+    ///
+    /// Names are compared ignoring ASCII case, since on macOS and Windows
+    /// names differing only in case are the same file.
+    pub fn claim(&self, dir_key: &str, file_name: &str) -> Option<&OutputClaim> {
+        self.directories
+            .get(dir_key)?
+            .iter()
+            .find(|claim| claim.file_name.eq_ignore_ascii_case(file_name))
+    }
+
+    /// Record a claim, replacing any earlier claim on the same name
+    pub fn record(&mut self, dir_key: &str, claim: OutputClaim) {
+        let claims = self.directories.entry(dir_key.to_string()).or_default();
+        claims.retain(|existing| !existing.file_name.eq_ignore_ascii_case(&claim.file_name));
+        claims.push(claim);
+    }
+
+    /// Drop claims on files no longer in the folder, returning how many
+    ///
+    /// Does nothing if the folder itself can't be found.
+    pub fn heal(&mut self, dir_key: &str) -> usize {
+        let dir = Path::new(dir_key);
+        if !dir.is_dir() {
+            return 0;
+        }
+        let Some(claims) = self.directories.get_mut(dir_key) else {
+            return 0;
+        };
+
+        let before = claims.len();
+        claims.retain(|claim| dir.join(&claim.file_name).exists());
+        let dropped = before - claims.len();
+        if claims.is_empty() {
+            self.directories.remove(dir_key);
+        }
+        dropped
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn claim(file_name: &str, vault_id: &str) -> OutputClaim {
+        OutputClaim {
+            file_name: file_name.to_string(),
+            vault_id: vault_id.to_string(),
+            vault_name: vault_id.to_string(),
+            recorded_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_record_replaces_claim_and_round_trips() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join(INDEX_FILENAME);
+        let key = OutputIndex::directory_key(temp_dir.path()).unwrap();
+
+        let mut index = OutputIndex::load_from(&path).unwrap();
+        index.record(&key, claim("2026-03-01.age", "vault-001"));
+        index.record(&key, claim("2026-03-01.AGE", "vault-002"));
+        assert_eq!(index.directories[&key].len(), 1);
+        assert_eq!(
+            index.claim(&key, "2026-03-01.age").unwrap().vault_id,
+            "vault-002"
+        );
+        index.save_to(&path).unwrap();
+
+        assert_eq!(OutputIndex::load_from(&path).unwrap(), index);
+    }
+
+    #[test]
+    fn test_heal_skips_unreachable_folders() {
+        let mut index = OutputIndex::default();
+        let key = "/nonexistent/barqly-output";
+        index.record(key, claim("2026-03-01.age", "vault-001"));
+
+        assert_eq!(index.heal(key), 0);
+        assert!(index.claim(key, "2026-03-01.age").is_some());
+    }
+}
@@ -0,0 +1,146 @@
+//! Statistics history
+//!
+//! Device-local series of vault statistics records, for trend charts.
+//! Stored as a single JSON file in the config directory, keyed by vault ID
+//! so a vault keeps its history when it is renamed or moved. Each vault's
+//! series is capped at `MAX_HISTORY_RECORDS`, dropping the oldest first.
+
+use crate::services::shared::infrastructure::io::atomic_write_sync;
+use crate::services::shared::infrastructure::path_management::get_config_dir;
+use crate::services::vault::domain::models::{MAX_HISTORY_RECORDS, StatisticsRecord};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tracing::debug;
+
+const HISTORY_FILENAME: &str = "statistics_history.json";
+const HISTORY_SCHEMA: &str = "barqly.vault.statistics-history/1";
+
+/// Statistics records per vault
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StatisticsHistory {
+    pub schema: String,
+    /// Oldest first per vault
+    #[serde(default)]
+    pub vaults: HashMap<String, Vec<StatisticsRecord>>,
+}
+
+impl Default for StatisticsHistory {
+    fn default() -> Self {
+        Self {
+            schema: HISTORY_SCHEMA.to_string(),
+            vaults: HashMap::new(),
+        }
+    }
+}
+
+impl StatisticsHistory {
+    pub fn get_history_path() -> Result<PathBuf, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(get_config_dir()?.join(HISTORY_FILENAME))
+    }
+
+    /// Load the history from the config directory (empty if none saved yet)
+    pub fn load() -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        Self::load_from(&Self::get_history_path()?)
+    }
+
+    /// Save the history to the config directory
+    pub fn save(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.save_to(&Self::get_history_path()?)
+    }
+
+    pub fn load_from(path: &Path) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        if !path.exists() {
+            debug!("Statistics history doesn't exist, starting empty");
+            return Ok(Self::default());
+        }
+
+        let content = fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&content)?)
+    }
+
+    pub fn save_to(&self, path: &Path) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let json = serde_json::to_string(self)?;
+        atomic_write_sync(path, json.as_bytes())?;
+        debug!(vault_count = self.vaults.len(), "Saved statistics history");
+        Ok(())
+    }
+
+    /// Records for a vault, oldest first
+    pub fn records(&self, vault_id: &str) -> &[StatisticsRecord] {
+        self.vaults.get(vault_id).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    /// Append a record, dropping the oldest beyond the cap
+    pub fn append(&mut self, vault_id: &str, record: StatisticsRecord) {
+        let records = self.vaults.entry(vault_id.to_string()).or_default();
+        records.push(record);
+        if records.len() > MAX_HISTORY_RECORDS {
+            let excess = records.len() - MAX_HISTORY_RECORDS;
+            records.drain(..excess);
+        }
+    }
+
+    /// Drop a deleted vault's history
+    pub fn remove_vault(&mut self, vault_id: &str) -> bool {
+        self.vaults.remove(vault_id).is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::ByteSize;
+    use chrono::{DateTime, Duration, Utc};
+    use tempfile::TempDir;
+
+    fn record(seq: i64) -> StatisticsRecord {
+        let start: DateTime<Utc> = "2026-01-01T00:00:00Z".parse().unwrap();
+        StatisticsRecord {
+            recorded_at: start + Duration::hours(seq),
+            archive_count: seq as usize,
+            archive_files: 1,
+            total_encrypted: ByteSize(seq as u64 * 100),
+            total_file_count: 4,
+            largest_archive: ByteSize(seq as u64 * 100),
+        }
+    }
+
+    #[test]
+    fn test_append_rotates_oldest() {
+        let mut history = StatisticsHistory::default();
+        for seq in 0..(MAX_HISTORY_RECORDS as i64 + 5) {
+            history.append("vault-001", record(seq));
+        }
+        history.append("vault-002", record(0));
+
+        let records = history.records("vault-001");
+        assert_eq!(records.len(), MAX_HISTORY_RECORDS);
+        assert_eq!(records.first(), Some(&record(5)));
+        assert_eq!(
+            records.last(),
+            Some(&record(MAX_HISTORY_RECORDS as i64 + 4))
+        );
+        assert_eq!(history.records("vault-002").len(), 1);
+    }
+
+    #[test]
+    fn test_round_trip_and_remove() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join(HISTORY_FILENAME);
+
+        let mut history = StatisticsHistory::load_from(&path).unwrap();
+        assert!(history.vaults.is_empty());
+        history.append("vault-001", record(1));
+        history.append("vault-002", record(2));
+        history.save_to(&path).unwrap();
+
+        let mut loaded = StatisticsHistory::load_from(&path).unwrap();
+        assert_eq!(loaded, history);
+        assert!(loaded.remove_vault("vault-001"));
+        assert!(!loaded.remove_vault("vault-001"));
+        assert!(loaded.records("vault-001").is_empty());
+        assert_eq!(loaded.records("vault-002"), &[record(2)]);
+    }
+}
@@ -0,0 +1,325 @@
+//! Vault generation ledger
+//!
+//! Stamps each write of a vault's manifest and archive index entries with the
+//! next generation and this device, and remembers what this device wrote in
+//! its local vault settings. A write is refused while the file on disk hides
+//! one of this device's earlier writes or a sync service left a conflicting
+//! copy beside it; `VaultConflictService` settles those.
+
+use crate::services::shared::infrastructure::device_identity::get_hostname;
+use crate::services::vault::domain::models::{
+    ConflictEvidence, GenerationFile, GenerationStamp, LastWriter, WrittenGenerations,
+};
+use crate::services::vault::infrastructure::persistence::app_config::AppConfig;
+use crate::services::vault::infrastructure::persistence::metadata::VaultMetadata;
+use crate::services::vault::infrastructure::persistence::vault_settings::VaultSettingsRegistry;
+use chrono::Utc;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tracing::{debug, warn};
+
+/// A write refused because the vault is suspected to be in conflict
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VaultConflictError {
+    pub vault_id: String,
+    pub evidence: ConflictEvidence,
+}
+
+impl fmt::Display for VaultConflictError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.evidence {
+            ConflictEvidence::HiddenWrite {
+                on_disk,
+                last_written,
+                writer,
+                ..
+            } => write!(
+                f,
+                "Vault '{}' was replaced by generation {} from {}, but this device last wrote generation {}",
+                self.vault_id,
+                on_disk,
+                writer
+                    .as_ref()
+                    .map_or("an unknown device", |w| w.hostname.as_str()),
+                last_written
+            ),
+            ConflictEvidence::ConflictedCopy { path, .. } => write!(
+                f,
+                "Vault '{}' has a conflicting copy at {}",
+                self.vault_id, path
+            ),
+        }
+    }
+}
+
+impl std::error::Error for VaultConflictError {}
+
+/// This device's identity and record of the generations it wrote
+#[derive(Debug, Clone)]
+pub struct GenerationLedger {
+    device_id: String,
+    hostname: String,
+    settings_path: PathBuf,
+}
+
+impl GenerationLedger {
+    /// Ledger of this profile, generating its device ID on first use
+    pub fn load() -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        let config_path = AppConfig::get_config_path()?;
+        let mut config = AppConfig::load_from(&config_path)?;
+        let (device_id, generated) = config.ensure_device_id();
+        if generated {
+            config.save_to(&config_path)?;
+            debug!(%device_id, "Generated device ID");
+        }
+
+        Ok(Self::at(
+            device_id,
+            get_hostname().unwrap_or_else(|| "unknown".to_string()),
+            VaultSettingsRegistry::get_settings_path()?,
+        ))
+    }
+
+    /// Ledger recording into the vault settings at `settings_path`
+    pub fn at(
+        device_id: impl Into<String>,
+        hostname: impl Into<String>,
+        settings_path: impl Into<PathBuf>,
+    ) -> Self {
+        Self {
+            device_id: device_id.into(),
+            hostname: hostname.into(),
+            settings_path: settings_path.into(),
+        }
+    }
+
+    pub fn device_id(&self) -> &str {
+        &self.device_id
+    }
+
+    /// This device as the writer of a file, now
+    pub fn writer(&self) -> LastWriter {
+        LastWriter {
+            device_id: self.device_id.clone(),
+            hostname: self.hostname.clone(),
+            timestamp: Utc::now(),
+        }
+    }
+
+    /// Last generations this device wrote for a vault
+    pub fn written(&self, vault_id: &str) -> WrittenGenerations {
+        match VaultSettingsRegistry::load_from(&self.settings_path) {
+            Ok(registry) => registry.get(vault_id).written_generations,
+            Err(e) => {
+                warn!(error = %e, "Failed to read written generations");
+                WrittenGenerations::default()
+            }
+        }
+    }
+
+    /// Remember writing `generation` of a vault's file
+    pub fn record(
+        &self,
+        vault_id: &str,
+        file: GenerationFile,
+        generation: u64,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let mut registry = VaultSettingsRegistry::load_from(&self.settings_path)?;
+        registry
+            .entry(vault_id)
+            .written_generations
+            .record(file, generation);
+        registry.save_to(&self.settings_path)
+    }
+
+    /// Evidence that `on_disk` hides one of this device's writes
+    pub fn hidden_write(
+        &self,
+        vault_id: &str,
+        file: GenerationFile,
+        on_disk: &GenerationStamp,
+    ) -> Option<ConflictEvidence> {
+        let last_written = self.written(vault_id).get(file);
+        on_disk
+            .hides_write(last_written, &self.device_id)
+            .then(|| ConflictEvidence::HiddenWrite {
+                file,
+                on_disk: on_disk.generation,
+                last_written,
+                writer: on_disk.last_writer.clone(),
+            })
+    }
+
+    /// Stamp for this device's next write over `on_disk`
+    pub fn next_stamp(
+        &self,
+        vault_id: &str,
+        file: GenerationFile,
+        on_disk: &GenerationStamp,
+    ) -> Result<GenerationStamp, VaultConflictError> {
+        if let Some(evidence) = self.hidden_write(vault_id, file, on_disk) {
+            return Err(VaultConflictError {
+                vault_id: vault_id.to_string(),
+                evidence,
+            });
+        }
+        Ok(self.stamp_over(vault_id, file, on_disk))
+    }
+
+    /// Stamp for this device's next write over `on_disk`, even one hiding an
+    /// earlier write, for replacing a file on purpose
+    pub fn stamp_over(
+        &self,
+        vault_id: &str,
+        file: GenerationFile,
+        on_disk: &GenerationStamp,
+    ) -> GenerationStamp {
+        on_disk.next(self.written(vault_id).get(file), self.writer())
+    }
+}
+
+/// Whether `stem` names a sync service's conflicting copy of `original_stem`
+///
+/// Matches e.g. Dropbox's `Family (laptop's conflicted copy 2024-05-01)` and
+/// Syncthing's `Family.sync-conflict-20240501-101500-ABCDEFG`.
+pub fn is_conflicted_copy(stem: &str, original_stem: &str) -> bool {
+    stem != original_stem
+        && stem
+            .strip_prefix(original_stem)
+            .is_some_and(|rest| rest.to_lowercase().contains("conflict"))
+}
+
+/// Conflicting copies of `path` in its directory, oldest name first
+pub fn conflicted_copies(path: &Path) -> Vec<PathBuf> {
+    let (Some(dir), Some(original_stem)) =
+        (path.parent(), path.file_stem().and_then(|s| s.to_str()))
+    else {
+        return Vec::new();
+    };
+    let extension = path.extension();
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    let mut copies: Vec<PathBuf> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|copy| copy.extension() == extension)
+        .filter(|copy| {
+            copy.file_stem()
+                .and_then(|s| s.to_str())
+                .is_some_and(|stem| is_conflicted_copy(stem, original_stem))
+        })
+        .collect();
+    copies.sort();
+    copies
+}
+
+/// Generation stamp of the manifest at `path` (default if none is there yet)
+pub fn manifest_stamp_at(path: &Path) -> GenerationStamp {
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+/// Conflicting copies of the manifest at `path` holding the same vault
+///
+/// Another vault whose name merely looks like a conflicted copy is left out.
+pub fn conflicted_manifest_copies(path: &Path, vault_id: &str) -> Vec<PathBuf> {
+    conflicted_copies(path)
+        .into_iter()
+        .filter(|copy| {
+            fs::read_to_string(copy)
+                .ok()
+                .and_then(|content| serde_json::from_str::<VaultMetadata>(&content).ok())
+                .is_some_and(|metadata| metadata.vault_id() == vault_id)
+        })
+        .collect()
+}
+
+/// Copy of `metadata` stamped for writing over the manifest at `path`
+///
+/// Record the stamp's generation with the ledger once the write succeeds.
+pub fn stamp_manifest(
+    ledger: &GenerationLedger,
+    metadata: &VaultMetadata,
+    path: &Path,
+) -> Result<VaultMetadata, VaultConflictError> {
+    if let Some(copy) = conflicted_manifest_copies(path, metadata.vault_id()).first() {
+        return Err(VaultConflictError {
+            vault_id: metadata.vault_id().to_string(),
+            evidence: ConflictEvidence::ConflictedCopy {
+                file: GenerationFile::Manifest,
+                path: copy.display().to_string(),
+            },
+        });
+    }
+
+    let mut stamped = metadata.clone();
+    stamped.generation_stamp = ledger.next_stamp(
+        metadata.vault_id(),
+        GenerationFile::Manifest,
+        &manifest_stamp_at(path),
+    )?;
+    Ok(stamped)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_conflicted_copy_names() {
+        assert!(is_conflicted_copy(
+            "Family (laptop's conflicted copy 2024-05-01)",
+            "Family"
+        ));
+        assert!(is_conflicted_copy(
+            "Family.sync-conflict-20240501-101500-ABCDEFG",
+            "Family"
+        ));
+        assert!(is_conflicted_copy(
+            "archive_index (Conflict)",
+            "archive_index"
+        ));
+
+        assert!(!is_conflicted_copy("Family", "Family"));
+        assert!(!is_conflicted_copy("Family Photos", "Family"));
+        // A vault actually named like a conflicted copy of another
+        assert!(!is_conflicted_copy("Conflict notes", "Family"));
+    }
+
+    #[test]
+    fn test_ledger_refuses_to_write_over_a_hidden_write() {
+        let temp = TempDir::new().unwrap();
+        let desktop = GenerationLedger::at("desktop", "desktop-host", temp.path().join("d.json"));
+        let laptop = GenerationLedger::at("laptop", "laptop-host", temp.path().join("l.json"));
+        let file = GenerationFile::Manifest;
+
+        let first = desktop
+            .next_stamp("vault-001", file, &GenerationStamp::default())
+            .unwrap();
+        desktop.record("vault-001", file, first.generation).unwrap();
+        let second = laptop.next_stamp("vault-001", file, &first).unwrap();
+        laptop.record("vault-001", file, second.generation).unwrap();
+        assert_eq!(second.generation, 2);
+        assert!(second.written_by("laptop"));
+
+        // The laptop's copy arrives over the desktop's own generation 3
+        let third = desktop.next_stamp("vault-001", file, &second).unwrap();
+        desktop.record("vault-001", file, third.generation).unwrap();
+        let error = desktop.next_stamp("vault-001", file, &second).unwrap_err();
+        assert_eq!(
+            error.evidence,
+            ConflictEvidence::HiddenWrite {
+                file,
+                on_disk: 2,
+                last_written: 3,
+                writer: second.last_writer.clone(),
+            }
+        );
+    }
+}
@@ -0,0 +1,236 @@
+//! Vault list order
+//!
+//! The user's order for the vault list: a favorite flag and a position per
+//! vault. Device-local, stored as a single JSON file in the config directory
+//! beside the app config, so each profile keeps its own order.
+//!
+//! Entries follow the vault registry through `reconcile`: vaults that are
+//! gone are dropped and vaults not yet placed are appended at the end.
+
+use crate::services::shared::infrastructure::io::atomic_write_sync;
+use crate::services::shared::infrastructure::path_management::get_config_dir;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tracing::debug;
+
+const ORDER_FILENAME: &str = "vault_order.json";
+const ORDER_SCHEMA: &str = "barqly.vault.vault-order/1";
+
+/// A vault's place in the list
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VaultOrderEntry {
+    pub vault_id: String,
+    #[serde(default)]
+    pub favorite: bool,
+    pub sort_index: u32,
+}
+
+/// Vault positions and favorites, kept in `sort_index` order
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VaultOrder {
+    pub schema: String,
+    #[serde(default)]
+    pub entries: Vec<VaultOrderEntry>,
+}
+
+/// How a requested order differs from the vaults that exist
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OrderMismatch {
+    /// Existing vaults the order leaves out
+    pub missing: Vec<String>,
+    /// Ids in the order that aren't vaults
+    pub unknown: Vec<String>,
+    /// Ids listed more than once
+    pub duplicates: Vec<String>,
+}
+
+impl fmt::Display for OrderMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut parts = Vec::new();
+        for (what, ids) in [
+            ("missing", &self.missing),
+            ("unknown", &self.unknown),
+            ("repeated", &self.duplicates),
+        ] {
+            if !ids.is_empty() {
+                parts.push(format!("{what}: {}", ids.join(", ")));
+            }
+        }
+        f.write_str(&parts.join("; "))
+    }
+}
+
+impl Default for VaultOrder {
+    fn default() -> Self {
+        Self {
+            schema: ORDER_SCHEMA.to_string(),
+            entries: Vec::new(),
+        }
+    }
+}
+
+impl VaultOrder {
+    pub fn get_order_path() -> Result<PathBuf, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(get_config_dir()?.join(ORDER_FILENAME))
+    }
+
+    pub fn load_from(path: &Path) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        if !path.exists() {
+            debug!("Vault order doesn't exist, starting empty");
+            return Ok(Self::default());
+        }
+
+        let content = fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&content)?)
+    }
+
+    pub fn save_to(&self, path: &Path) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let json = serde_json::to_string_pretty(self)?;
+        atomic_write_sync(path, json.as_bytes())?;
+        debug!(vault_count = self.entries.len(), "Saved vault order");
+        Ok(())
+    }
+
+    pub fn entry(&self, vault_id: &str) -> Option<&VaultOrderEntry> {
+        self.entries.iter().find(|entry| entry.vault_id == vault_id)
+    }
+
+    /// Match the entries to the vaults in `vault_ids`, returning whether
+    /// anything changed
+    ///
+    /// Entries of other vaults are dropped, vaults without an entry are
+    /// appended in the order given, and positions are renumbered from 0.
+    pub fn reconcile(&mut self, vault_ids: &[&str]) -> bool {
+        let before = self.entries.clone();
+        let existing: HashSet<&str> = vault_ids.iter().copied().collect();
+
+        self.entries.sort_by_key(|entry| entry.sort_index);
+        self.entries
+            .retain(|entry| existing.contains(entry.vault_id.as_str()));
+        for vault_id in vault_ids {
+            if self.entry(vault_id).is_none() {
+                self.entries.push(VaultOrderEntry {
+                    vault_id: vault_id.to_string(),
+                    favorite: false,
+                    sort_index: 0,
+                });
+            }
+        }
+        self.renumber();
+        self.entries != before
+    }
+
+    /// Put the vaults in the order of `ordered_ids`, keeping favorites
+    ///
+    /// `ordered_ids` must name every entry exactly once, so reconcile first.
+    pub fn reorder(&mut self, ordered_ids: &[String]) -> Result<(), OrderMismatch> {
+        let mut mismatch = OrderMismatch::default();
+        let mut seen = HashSet::new();
+        for vault_id in ordered_ids {
+            if !seen.insert(vault_id.as_str()) {
+                mismatch.duplicates.push(vault_id.clone());
+            } else if self.entry(vault_id).is_none() {
+                mismatch.unknown.push(vault_id.clone());
+            }
+        }
+        mismatch.missing = self
+            .entries
+            .iter()
+            .filter(|entry| !seen.contains(entry.vault_id.as_str()))
+            .map(|entry| entry.vault_id.clone())
+            .collect();
+        if mismatch != OrderMismatch::default() {
+            return Err(mismatch);
+        }
+
+        let position = |vault_id: &str| ordered_ids.iter().position(|id| id == vault_id);
+        self.entries
+            .sort_by_key(|entry| position(&entry.vault_id).unwrap_or(usize::MAX));
+        self.renumber();
+        Ok(())
+    }
+
+    /// Mark or unmark a vault as a favorite; `false` if it has no entry
+    pub fn set_favorite(&mut self, vault_id: &str, favorite: bool) -> bool {
+        match self
+            .entries
+            .iter_mut()
+            .find(|entry| entry.vault_id == vault_id)
+        {
+            Some(entry) => {
+                entry.favorite = favorite;
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn renumber(&mut self) {
+        for (index, entry) in self.entries.iter_mut().enumerate() {
+            entry.sort_index = index as u32;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn ids(order: &VaultOrder) -> Vec<&str> {
+        order
+            .entries
+            .iter()
+            .map(|entry| entry.vault_id.as_str())
+            .collect()
+    }
+
+    fn strings(ids: &[&str]) -> Vec<String> {
+        ids.iter().map(|id| id.to_string()).collect()
+    }
+
+    #[test]
+    fn test_reorder_rejects_a_stale_id_set() {
+        let mut order = VaultOrder::default();
+        order.reconcile(&["a", "b", "c"]);
+
+        let err = order
+            .reorder(&strings(&["c", "a", "gone", "a"]))
+            .unwrap_err();
+        assert_eq!(err.missing, vec!["b"]);
+        assert_eq!(err.unknown, vec!["gone"]);
+        assert_eq!(err.duplicates, vec!["a"]);
+        assert_eq!(ids(&order), vec!["a", "b", "c"]);
+
+        order.reorder(&strings(&["c", "a", "b"])).unwrap();
+        assert_eq!(ids(&order), vec!["c", "a", "b"]);
+        let indices: Vec<u32> = order.entries.iter().map(|e| e.sort_index).collect();
+        assert_eq!(indices, vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn test_reconcile_drops_deleted_and_appends_new_vaults() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join(ORDER_FILENAME);
+
+        let mut order = VaultOrder::load_from(&path).unwrap();
+        order.reconcile(&["a", "b", "c", "d"]);
+        order.reorder(&strings(&["d", "c", "b", "a"])).unwrap();
+        assert!(order.set_favorite("b", true));
+        order.save_to(&path).unwrap();
+
+        // "c" deleted, "e" created since the reorder
+        let mut order = VaultOrder::load_from(&path).unwrap();
+        assert!(order.reconcile(&["a", "b", "d", "e"]));
+        assert_eq!(ids(&order), vec!["d", "b", "a", "e"]);
+        assert_eq!(order.entry("a").unwrap().sort_index, 2);
+        assert!(order.entry("b").unwrap().favorite);
+        assert!(order.entry("c").is_none());
+
+        assert!(!order.reconcile(&["e", "d", "b", "a"]));
+        assert!(!order.set_favorite("c", true));
+    }
+}
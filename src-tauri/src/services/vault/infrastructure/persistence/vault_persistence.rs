@@ -3,19 +3,29 @@
 //! Handles saving and loading vault metadata from the file system.
 
 use crate::prelude::*;
-use crate::services::shared::infrastructure::io::atomic_write;
+use crate::services::shared::infrastructure::io::{
+    PendingWrite, StoreLock, atomic_write, atomic_write_sync,
+};
 use crate::services::shared::infrastructure::path_management::{
-    get_vault_manifest_path, get_vaults_manifest_dir, sanitize_vault_name,
+    get_vault_manifest_dirs, get_vault_manifest_path, sanitize_vault_name,
 };
+use crate::services::vault::domain::models::GenerationFile;
+use crate::services::vault::infrastructure::persistence::manifest_signing::signed_manifest_json;
 use crate::services::vault::infrastructure::persistence::metadata::VaultMetadata;
-use std::path::PathBuf;
+use crate::services::vault::infrastructure::persistence::vault_generation::{
+    GenerationLedger, is_conflicted_copy, stamp_manifest,
+};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use std::sync::Once;
 use tokio::fs as async_fs;
 
-/// Get the vaults manifest directory path (non-sync location for vault metadata)
-fn get_vaults_dir() -> Result<PathBuf, Box<dyn std::error::Error + Send + Sync>> {
-    let vaults_dir = get_vaults_manifest_dir()?;
-    Ok(vaults_dir)
+/// Get the vault manifest directories (non-sync locations for vault metadata)
+///
+/// The shared store's directory comes first when this profile uses one.
+fn get_vaults_dirs() -> Result<Vec<PathBuf>, Box<dyn std::error::Error + Send + Sync>> {
+    let vaults_dirs = get_vault_manifest_dirs()?;
+    Ok(vaults_dirs)
 }
 
 /// Get the path for a specific vault's manifest file
@@ -31,17 +41,49 @@ fn get_vault_path_by_name(
 
 /// Save vault metadata to disk
 /// Saves to non-sync location: ~/Library/.../vaults/ using the sanitized name as the filename
+///
+/// Fails with `VaultConflictError` while the vault is suspected to be in
+/// conflict with another device's copy.
 pub async fn save_vault(
     metadata: &VaultMetadata,
 ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     // Use sanitized name for the filename
     let path = get_vault_path_by_name(&metadata.vault.sanitized_name)?;
-    let json = serde_json::to_string_pretty(metadata)?;
+    let ledger = GenerationLedger::load()?;
+    let stamped = stamp_manifest(&ledger, metadata, &path)?;
+    let json = signed_manifest_json(&stamped)?;
 
     // Atomic write with sync_all() for durability
     atomic_write(&path, json.as_bytes()).await?;
 
-    Ok(())
+    record_manifest_write(&ledger, &stamped)
+}
+
+/// Serialized vault metadata as a write for a journaled mutation
+///
+/// The generation is recorded as written straight away, as the journal
+/// commits the write later.
+pub fn vault_pending_write(
+    metadata: &VaultMetadata,
+) -> Result<PendingWrite, Box<dyn std::error::Error + Send + Sync>> {
+    let path = get_vault_path_by_name(&metadata.vault.sanitized_name)?;
+    let ledger = GenerationLedger::load()?;
+    let stamped = stamp_manifest(&ledger, metadata, &path)?;
+    let json = signed_manifest_json(&stamped)?;
+    record_manifest_write(&ledger, &stamped)?;
+    Ok(PendingWrite::new(path, json.into_bytes()))
+}
+
+/// Remember the generation of a manifest this device wrote
+pub fn record_manifest_write(
+    ledger: &GenerationLedger,
+    stamped: &VaultMetadata,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    ledger.record(
+        stamped.vault_id(),
+        GenerationFile::Manifest,
+        stamped.generation_stamp.generation,
+    )
 }
 
 /// Load vault metadata from disk by name
@@ -98,6 +140,69 @@ pub async fn vault_exists(vault_id: &str) -> bool {
     }
 }
 
+/// Check if a vault exists by ID without awaiting
+///
+/// For input validation, which runs before a command does any async work.
+pub fn vault_exists_sync(vault_id: &str) -> bool {
+    find_vault_sync(vault_id).is_some()
+}
+
+/// Load a vault's metadata by ID without awaiting
+pub fn find_vault_sync(vault_id: &str) -> Option<VaultMetadata> {
+    let dirs = get_vaults_dirs().ok()?;
+    dirs.iter()
+        .rev()
+        .flat_map(|dir| manifests_in(dir))
+        .find(|metadata| metadata.vault_id() == vault_id)
+}
+
+/// Every vault manifest in `dir`, skipping ones that fail to parse and
+/// conflicting copies a sync service left
+pub fn manifests_in(dir: &Path) -> Vec<VaultMetadata> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            let extension = path.extension().and_then(|s| s.to_str());
+            let stem = path
+                .file_stem()
+                .and_then(|s| s.to_str())
+                .unwrap_or_default();
+            matches!(extension, Some("manifest" | "json"))
+                && !stem.ends_with(".tmp")
+                && !stem.ends_with(".bak")
+        })
+        .filter_map(|path| {
+            let content = std::fs::read_to_string(&path).ok()?;
+            let metadata = serde_json::from_str::<VaultMetadata>(&content).ok()?;
+            (!is_copy_of(&path, &metadata)).then_some(metadata)
+        })
+        .collect()
+}
+
+/// Whether `path` is a sync service's conflicting copy of `metadata`'s manifest
+fn is_copy_of(path: &Path, metadata: &VaultMetadata) -> bool {
+    path.file_stem()
+        .and_then(|s| s.to_str())
+        .is_some_and(|stem| is_conflicted_copy(stem, &metadata.vault.sanitized_name))
+}
+
+/// Save vault metadata without awaiting
+pub fn save_vault_sync(
+    metadata: &VaultMetadata,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let path = get_vault_path_by_name(&metadata.vault.sanitized_name)?;
+    let ledger = GenerationLedger::load()?;
+    let stamped = stamp_manifest(&ledger, metadata, &path)?;
+    let json = signed_manifest_json(&stamped)?;
+    atomic_write_sync(&path, json.as_bytes())?;
+    record_manifest_write(&ledger, &stamped)
+}
+
 /// Delete a vault by name
 pub async fn delete_vault_by_name(
     vault_name: &str,
@@ -106,7 +211,9 @@ pub async fn delete_vault_by_name(
 
     if manifest_path.exists() {
         // Delete the vault manifest file (no backup creation)
+        let store_lock = StoreLock::for_write(&manifest_path)?;
         async_fs::remove_file(&manifest_path).await?;
+        drop(store_lock);
 
         // Delete the corresponding .age file from Barqly-Vaults directory if it exists
         use crate::services::shared::infrastructure::path_management::get_vaults_directory;
@@ -149,53 +256,56 @@ pub async fn delete_vault(vault_id: &str) -> Result<(), Box<dyn std::error::Erro
 static LIST_VAULTS_LOGGED: Once = Once::new();
 
 /// List all vaults
+///
+/// With a shared store, lists the store's vaults and this profile's own. A
+/// vault in both is listed once, with the profile's copy, as that's the one
+/// `get_vault_manifest_path` saves to.
 pub async fn list_vaults() -> Result<Vec<VaultMetadata>, Box<dyn std::error::Error + Send + Sync>> {
-    let vaults_dir = get_vaults_dir()?;
+    let vaults_dirs = get_vaults_dirs()?;
 
     // Only log initial vault listing once per app session
     LIST_VAULTS_LOGGED.call_once(|| {
-        debug!(path = %vaults_dir.display(), "Initial vault listing");
+        debug!(dirs = ?vaults_dirs, "Initial vault listing");
     });
 
-    let mut vaults = Vec::new();
+    list_vaults_in(&vaults_dirs).await
+}
+
+/// List the vaults in `dirs`, a later directory's copy replacing an earlier one
+pub async fn list_vaults_in(
+    dirs: &[PathBuf],
+) -> Result<Vec<VaultMetadata>, Box<dyn std::error::Error + Send + Sync>> {
+    let mut vaults: HashMap<String, VaultMetadata> = HashMap::new();
 
-    if vaults_dir.exists() {
-        let mut entries = async_fs::read_dir(&vaults_dir).await.map_err(|e| {
+    for vaults_dir in dirs.iter().filter(|dir| dir.exists()) {
+        let mut entries = async_fs::read_dir(vaults_dir).await.map_err(|e| {
             error!(error = %e, "Failed to read vaults directory");
             e
         })?;
 
         while let Some(entry) = entries.next_entry().await? {
             let path = entry.path();
-
-            // Process .manifest files
-            if path.extension().and_then(|s| s.to_str()) == Some("manifest") {
-                if let Some(stem) = path.file_stem().and_then(|s| s.to_str()) {
-                    // Skip temp files and backup files
-                    if !stem.ends_with(".tmp") && !stem.ends_with(".bak") {
-                        // Load vault metadata by name (stem is the vault name)
-                        if let Ok(metadata) = load_vault_by_name(stem).await {
-                            vaults.push(metadata);
-                        }
-                    }
-                }
-            }
-            // Also check for .json vault files (legacy support)
-            else if path.extension().and_then(|s| s.to_str()) == Some("json")
-                && let Some(stem) = path.file_stem().and_then(|s| s.to_str())
+            let extension = path.extension().and_then(|s| s.to_str());
+            let Some(stem) = path.file_stem().and_then(|s| s.to_str()) else {
+                continue;
+            };
+
+            // .manifest files, and .json vault files (legacy support);
+            // skip temp files, backup files and conflicting copies
+            if matches!(extension, Some("manifest" | "json"))
+                && !stem.ends_with(".tmp")
+                && !stem.ends_with(".bak")
+                && let Ok(content) = async_fs::read_to_string(&path).await
+                && let Ok(metadata) = serde_json::from_str::<VaultMetadata>(&content)
+                && !is_copy_of(&path, &metadata)
             {
-                // Skip temp files, try to load as VaultMetadata
-                if !stem.ends_with(".tmp")
-                    && let Ok(content) = async_fs::read_to_string(&path).await
-                    && let Ok(metadata) = serde_json::from_str::<VaultMetadata>(&content)
-                {
-                    vaults.push(metadata);
-                }
+                vaults.insert(metadata.vault_id().to_string(), metadata);
             }
         }
     }
 
     // Sort by creation date
+    let mut vaults: Vec<VaultMetadata> = vaults.into_values().collect();
     vaults.sort_by_key(|a| a.created_at());
 
     Ok(vaults)
@@ -419,4 +529,56 @@ async fn temp_delete_vault_by_name(
             .await
             .unwrap();
     }
+
+    #[tokio::test]
+    async fn test_profiles_list_shared_and_own_vaults() {
+        use tempfile::TempDir;
+
+        let temp_dir = TempDir::new().unwrap();
+        let shared = temp_dir.path().join("Shared/Barqly/vaults");
+        let alice = temp_dir.path().join("alice/vaults");
+        let bob = temp_dir.path().join("bob/vaults");
+        for dir in [&shared, &alice, &bob] {
+            std::fs::create_dir_all(dir).unwrap();
+        }
+
+        let device_info = DeviceInfo {
+            machine_id: "family-imac".to_string(),
+            machine_label: "Family iMac".to_string(),
+            created_at: Utc::now(),
+            app_version: "2.0.0".to_string(),
+        };
+        let write = |dir: &Path, id: &str, label: &str| {
+            let metadata = VaultMetadata::new(
+                id.to_string(),
+                label.to_string(),
+                None,
+                label.to_string(),
+                &device_info,
+                None,
+                vec![],
+                vec![],
+                0,
+                0,
+            );
+            let path = dir.join(format!("{label}.manifest"));
+            std::fs::write(path, serde_json::to_string(&metadata).unwrap()).unwrap();
+        };
+        write(&shared, "family_id", "Family");
+        write(&alice, "alice_id", "Alice-Taxes");
+        write(&bob, "bob_id", "Bob-Photos");
+        // A leftover copy in Alice's profile is the one she saves to
+        write(&alice, "family_id", "Family-Old");
+
+        let labels = |vaults: Vec<VaultMetadata>| {
+            let mut labels: Vec<String> = vaults.iter().map(|v| v.label().to_string()).collect();
+            labels.sort();
+            labels
+        };
+        let alices = list_vaults_in(&[shared.clone(), alice]).await.unwrap();
+        assert_eq!(labels(alices), vec!["Alice-Taxes", "Family-Old"]);
+        let bobs = list_vaults_in(&[shared.clone(), bob]).await.unwrap();
+        assert_eq!(labels(bobs), vec!["Bob-Photos", "Family"]);
+        assert_eq!(manifests_in(&shared).len(), 1);
+    }
 }
@@ -0,0 +1,196 @@
+//! Per-vault local settings
+//!
+//! Device-local preferences that should not travel with the vault manifest
+//! (notification preferences and snoozes, post-operation hooks, archive
+//! retention policy, encryption parameters, dead man's switch, whether
+//! reserved YubiKey slots hold back the protection policy, pre-encryption
+//! transforms, operations a forced quit interrupted, the last generations
+//! this device wrote to the vault's shared files). Stored as a single JSON
+//! file in the config directory, keyed by vault ID.
+
+use crate::services::crypto::domain::models::EncryptionParameters;
+use crate::services::file::infrastructure::file_operations::TransformSettings;
+use crate::services::shared::infrastructure::io::atomic_write_sync;
+use crate::services::shared::infrastructure::path_management::get_config_dir;
+use crate::services::vault::domain::models::{
+    DeadMansSwitch, InterruptedOperation, NotificationCategory, NotificationPreferences,
+    RetentionPolicy, VaultHooks, WrittenGenerations,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tracing::debug;
+
+const SETTINGS_FILENAME: &str = "vault_settings.json";
+const SETTINGS_SCHEMA: &str = "barqly.vault.settings/1";
+
+/// A dismissed notification and when it may reappear
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NotificationSnooze {
+    pub dismissed_at: DateTime<Utc>,
+    pub snoozed_until: DateTime<Utc>,
+}
+
+/// Local settings for a single vault
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VaultSettings {
+    #[serde(default)]
+    pub notification_preferences: NotificationPreferences,
+
+    /// Active snoozes by notification category
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub notification_snoozes: HashMap<NotificationCategory, NotificationSnooze>,
+
+    /// When each currently-triggered notification first fired
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub notification_first_triggered: HashMap<NotificationCategory, DateTime<Utc>>,
+
+    /// Programs to run after operations on this vault
+    #[serde(default, skip_serializing_if = "VaultHooks::is_empty")]
+    pub hooks: VaultHooks,
+
+    /// Which archives to keep when pruning
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retention_policy: Option<RetentionPolicy>,
+
+    /// Compression and file hash for new archives, from the last migration
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub encryption_parameters: Option<EncryptionParameters>,
+
+    /// Check-in schedule and the message shown once it lapses
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dead_mans_switch: Option<DeadMansSwitch>,
+
+    /// Let reserved YubiKey slots stand without failing the protection policy
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub allow_pending_yubikeys: bool,
+
+    /// Conversions applied to copies of files before they're archived
+    #[serde(default, skip_serializing_if = "TransformSettings::is_default")]
+    pub transforms: TransformSettings,
+
+    /// Operations cut short by a forced quit, until the user has seen them
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub interrupted_operations: Vec<InterruptedOperation>,
+
+    /// Last manifest and index generations this device wrote, to notice a
+    /// sync service replacing them with another device's copies
+    #[serde(default, skip_serializing_if = "WrittenGenerations::is_default")]
+    pub written_generations: WrittenGenerations,
+}
+
+impl VaultSettings {
+    /// True if the category is snoozed at `now`
+    pub fn is_snoozed(&self, category: NotificationCategory, now: DateTime<Utc>) -> bool {
+        self.notification_snoozes
+            .get(&category)
+            .is_some_and(|snooze| snooze.snoozed_until > now)
+    }
+}
+
+/// All per-vault settings on this device
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VaultSettingsRegistry {
+    pub schema: String,
+    #[serde(default)]
+    pub vaults: HashMap<String, VaultSettings>,
+}
+
+impl Default for VaultSettingsRegistry {
+    fn default() -> Self {
+        Self {
+            schema: SETTINGS_SCHEMA.to_string(),
+            vaults: HashMap::new(),
+        }
+    }
+}
+
+impl VaultSettingsRegistry {
+    pub(crate) fn get_settings_path() -> Result<PathBuf, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(get_config_dir()?.join(SETTINGS_FILENAME))
+    }
+
+    /// Load settings from the config directory (empty if none saved yet)
+    pub fn load() -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        Self::load_from(&Self::get_settings_path()?)
+    }
+
+    /// Save settings to the config directory
+    pub fn save(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.save_to(&Self::get_settings_path()?)
+    }
+
+    pub fn load_from(path: &Path) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        if !path.exists() {
+            debug!("Vault settings don't exist, using defaults");
+            return Ok(Self::default());
+        }
+
+        let content = fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&content)?)
+    }
+
+    pub fn save_to(&self, path: &Path) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let json = serde_json::to_string_pretty(self)?;
+        atomic_write_sync(path, json.as_bytes())?;
+        debug!(vault_count = self.vaults.len(), "Saved vault settings");
+        Ok(())
+    }
+
+    /// Settings for a vault (defaults if none stored)
+    pub fn get(&self, vault_id: &str) -> VaultSettings {
+        self.vaults.get(vault_id).cloned().unwrap_or_default()
+    }
+
+    /// Mutable settings for a vault, created on first access
+    pub fn entry(&mut self, vault_id: &str) -> &mut VaultSettings {
+        self.vaults.entry(vault_id.to_string()).or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_missing_file_loads_defaults() {
+        let temp = TempDir::new().unwrap();
+        let registry = VaultSettingsRegistry::load_from(&temp.path().join("none.json")).unwrap();
+
+        assert!(registry.vaults.is_empty());
+        assert_eq!(
+            registry.get("vault-001").notification_preferences,
+            NotificationPreferences::default()
+        );
+    }
+
+    #[test]
+    fn test_round_trip_with_snooze() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join(SETTINGS_FILENAME);
+        let now = Utc::now();
+
+        let mut registry = VaultSettingsRegistry::default();
+        let settings = registry.entry("vault-001");
+        settings.notification_preferences.stale_backup_days = 14;
+        settings.notification_snoozes.insert(
+            NotificationCategory::StaleBackup,
+            NotificationSnooze {
+                dismissed_at: now,
+                snoozed_until: now + chrono::Duration::days(7),
+            },
+        );
+        registry.save_to(&path).unwrap();
+
+        let loaded = VaultSettingsRegistry::load_from(&path).unwrap();
+        assert_eq!(loaded, registry);
+        assert!(
+            loaded
+                .get("vault-001")
+                .is_snoozed(NotificationCategory::StaleBackup, now)
+        );
+    }
+}
@@ -1,5 +1,7 @@
+use crate::services::shared::infrastructure::io::StoreLockError;
 use crate::services::vault;
 use crate::services::vault::domain::{VaultError, VaultResult};
+use crate::services::vault::infrastructure::persistence::VaultConflictError;
 use crate::services::vault::infrastructure::persistence::metadata::VaultMetadata;
 
 #[derive(Debug)]
@@ -12,9 +14,7 @@ pub fn new() -> Self {
 
     /// Save vault metadata to storage
     pub async fn save_vault(&self, metadata: &VaultMetadata) -> VaultResult<()> {
-        vault::save_vault(metadata)
-            .await
-            .map_err(|e| VaultError::StorageError(e.to_string()))
+        vault::save_vault(metadata).await.map_err(storage_error)
     }
 
     /// Load vault metadata from storage
@@ -45,9 +45,20 @@ pub async fn vault_exists(&self, vault_name: &str) -> VaultResult<bool> {
 
     /// Delete vault
     pub async fn delete_vault(&self, vault_id: &str) -> VaultResult<()> {
-        vault::delete_vault(vault_id)
-            .await
-            .map_err(|e| VaultError::StorageError(e.to_string()))
+        vault::delete_vault(vault_id).await.map_err(storage_error)
+    }
+}
+
+/// A failed write, naming the other account when the shared store is locked
+/// and the conflicting copy when the vault is suspected to be in conflict
+pub(crate) fn storage_error(e: Box<dyn std::error::Error + Send + Sync>) -> VaultError {
+    let e = match e.downcast::<StoreLockError>() {
+        Ok(lock_error) => return VaultError::from(*lock_error),
+        Err(e) => e,
+    };
+    match e.downcast::<VaultConflictError>() {
+        Ok(conflict) => VaultError::from(*conflict),
+        Err(e) => VaultError::StorageError(e.to_string()),
     }
 }
 
@@ -8,5 +8,6 @@
 // Re-export infrastructure persistence functions (replacing storage::vault_store)
 pub use infrastructure::{
     MetadataStorage, RecipientInfo, RecipientType, VaultMetadata, delete_vault, get_current_vault,
-    get_vault, list_vaults, load_vault, save_vault, vault_exists,
+    get_vault, list_vaults, load_vault, save_vault, vault_exists, vault_exists_sync,
+    vault_pending_write,
 };
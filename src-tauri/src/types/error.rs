@@ -2,7 +2,9 @@
 //!
 //! This module defines the CommandError struct used for all command error handling.
 
-use super::ErrorCode;
+use super::error_help::has_error_help;
+use super::{ErrorCode, ValidationFailure};
+use crate::logging::current_trace_id;
 use serde::{Deserialize, Serialize};
 use std::fmt;
 
@@ -25,6 +27,8 @@
 ///   user_actionable: boolean;
 ///   trace_id?: string;
 ///   span_id?: string;
+///   validation?: ValidationFailure;
+///   help_available: boolean;
 /// }
 /// ```
 #[derive(Debug, Serialize, Deserialize, specta::Type)]
@@ -39,10 +43,16 @@ pub struct CommandError {
     pub recovery_guidance: Option<String>,
     /// Whether the user can take action to resolve this error
     pub user_actionable: bool,
-    /// Optional trace ID for debugging
+    /// Trace ID of the failed command, for `get_logs_for_trace`
     pub trace_id: Option<String>,
     /// Optional span ID for debugging
     pub span_id: Option<String>,
+    /// Offending field and rule, for `ErrorCode::ValidationFailed`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub validation: Option<ValidationFailure>,
+    /// Whether `get_error_help` has troubleshooting steps for `code`
+    #[serde(default)]
+    pub help_available: bool,
 }
 
 impl CommandError {
@@ -54,8 +64,25 @@ pub fn validation(message: impl Into<String>) -> Self {
             details: None,
             recovery_guidance: None,
             user_actionable: true,
-            trace_id: None,
+            trace_id: current_trace_id(),
             span_id: None,
+            validation: None,
+            help_available: has_error_help(&ErrorCode::InvalidInput),
+        }
+    }
+
+    /// Create a structured validation error for one input field
+    pub fn validation_failed(failure: ValidationFailure, message: impl Into<String>) -> Self {
+        Self {
+            code: ErrorCode::ValidationFailed,
+            message: message.into(),
+            details: None,
+            recovery_guidance: None,
+            user_actionable: true,
+            trace_id: current_trace_id(),
+            span_id: None,
+            validation: Some(failure),
+            help_available: has_error_help(&ErrorCode::ValidationFailed),
         }
     }
 
@@ -67,8 +94,10 @@ pub fn permission(message: impl Into<String>) -> Self {
             details: None,
             recovery_guidance: Some("Check file permissions and try again".to_string()),
             user_actionable: true,
-            trace_id: None,
+            trace_id: current_trace_id(),
             span_id: None,
+            validation: None,
+            help_available: has_error_help(&ErrorCode::PermissionDenied),
         }
     }
 
@@ -80,8 +109,10 @@ pub fn not_found(message: impl Into<String>) -> Self {
             details: None,
             recovery_guidance: Some("Verify the key exists and try again".to_string()),
             user_actionable: true,
-            trace_id: None,
+            trace_id: current_trace_id(),
             span_id: None,
+            validation: None,
+            help_available: has_error_help(&ErrorCode::KeyNotFound),
         }
     }
 
@@ -89,14 +120,17 @@ pub fn not_found(message: impl Into<String>) -> Self {
     pub fn operation(code: ErrorCode, message: impl Into<String>) -> Self {
         let (recovery_guidance, user_actionable) =
             super::error_recovery::get_recovery_guidance(&code);
+        let help_available = has_error_help(&code);
         Self {
             code,
             message: message.into(),
             details: None,
             recovery_guidance,
             user_actionable,
-            trace_id: None,
+            trace_id: current_trace_id(),
             span_id: None,
+            validation: None,
+            help_available,
         }
     }
 
@@ -180,9 +214,55 @@ fn from(boxed_error: Box<CommandError>) -> Self {
     }
 }
 
+impl From<crate::services::shared::infrastructure::progress::OperationConflict> for CommandError {
+    fn from(
+        conflict: crate::services::shared::infrastructure::progress::OperationConflict,
+    ) -> Self {
+        CommandError::operation(ErrorCode::ConcurrentOperation, conflict.to_string())
+    }
+}
+
+impl From<crate::services::shared::infrastructure::io::StoreLockError> for CommandError {
+    fn from(error: crate::services::shared::infrastructure::io::StoreLockError) -> Self {
+        use crate::services::shared::infrastructure::io::StoreLockError;
+
+        match &error {
+            StoreLockError::InUse { .. } => {
+                CommandError::operation(ErrorCode::StoreInUse, error.to_string())
+            }
+            StoreLockError::Io(_) => {
+                CommandError::operation(ErrorCode::StorageFailed, error.to_string())
+                    .with_recovery_guidance("Check that the shared store folder is writable")
+            }
+        }
+    }
+}
+
 // Add support for YubiKey domain errors
 impl From<crate::services::key_management::yubikey::domain::errors::YubiKeyError> for CommandError {
     fn from(error: crate::services::key_management::yubikey::domain::errors::YubiKeyError) -> Self {
+        use crate::services::key_management::yubikey::domain::errors::YubiKeyError;
+
+        if let YubiKeyError::PluginProtocolMismatch { .. } = &error {
+            let guidance = error.recovery_guidance().unwrap_or_default();
+            return CommandError::operation(ErrorCode::PluginVersionMismatch, error.to_string())
+                .with_recovery_guidance(guidance);
+        }
+
+        let code = match &error {
+            YubiKeyError::ReaderSerialMismatch { .. } => Some(ErrorCode::WrongYubiKey),
+            YubiKeyError::ReaderNotFound { .. } => Some(ErrorCode::YubiKeyNotFound),
+            YubiKeyError::PinBlocked { .. } => Some(ErrorCode::YubiKeyPinBlocked),
+            YubiKeyError::LastAttemptGuard { .. } => Some(ErrorCode::YubiKeyLastPinAttempt),
+            YubiKeyError::PinAttemptDebounced { .. } => Some(ErrorCode::YubiKeyPinRequired),
+            _ => None,
+        };
+        if let Some(code) = code {
+            let guidance = error.recovery_guidance().unwrap_or_default();
+            return CommandError::operation(code, error.to_string())
+                .with_recovery_guidance(guidance);
+        }
+
         CommandError::operation(
             ErrorCode::YubiKeyInitializationFailed,
             format!("YubiKey operation failed: {error}"),
@@ -34,6 +34,10 @@ pub enum ErrorCode {
     InvalidFileFormat,
     FileTooLarge,
     TooManyFiles,
+    /// The selection contains the app's data, vault storage or output archive
+    SelectionOverlapsAppData,
+    /// An input field broke a declared rule; see `CommandError::validation`
+    ValidationFailed,
 
     // Permission errors
     PermissionDenied,
@@ -43,6 +47,7 @@ pub enum ErrorCode {
 
     // Not found errors
     KeyNotFound,
+    KeyMediaNotPresent,
     FileNotFound,
     DirectoryNotFound,
     OperationNotFound,
@@ -73,6 +78,7 @@ pub enum ErrorCode {
     YubiKeyNotFound,
     YubiKeyPinRequired,
     YubiKeyPinBlocked,
+    YubiKeyLastPinAttempt,
     YubiKeyTouchRequired,
     YubiKeyTouchTimeout,
     WrongYubiKey,
@@ -84,10 +90,19 @@ pub enum ErrorCode {
     VaultNotFound,
     VaultAlreadyExists,
     VaultKeyLimitExceeded,
+    ArchiveImmutable,
+    /// Another vault already has an archive of that name in the output folder
+    CrossVaultNameCollision,
+    /// Another OS account is changing the shared vault store
+    StoreInUse,
+    /// Another device's copy replaced this one's; resolve before changing it
+    VaultConflictSuspected,
 
     // Key Management Errors
     KeyAlreadyExists,
     InvalidKeyState,
+    /// Removing the key orphans archives and the removal plan wasn't confirmed
+    KeyRemovalUnconfirmed,
 
     // Plugin Errors
     PluginNotFound,
@@ -95,6 +110,11 @@ pub enum ErrorCode {
     PluginExecutionFailed,
     PluginDeploymentFailed,
 
+    // Compatibility Errors
+    AppVersionTooOld,
+    /// Feature is experimental and switched off, or not part of this build
+    FeatureDisabled,
+
     // Multi-recipient Errors
     NoUnlockMethodAvailable,
     RecipientMismatch,
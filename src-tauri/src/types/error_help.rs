@@ -0,0 +1,1127 @@
+//! Troubleshooting knowledge base
+//!
+//! Offline help for every error code and YubiKey failure category: likely
+//! causes, step-by-step remediation and the diagnostic commands worth
+//! running. Steps can mention the failing path, YubiKey serial, vault or
+//! key; `ErrorHelpContext` fills those in from what the error carried.
+//!
+//! Entries are embedded in English. Other locales fall back to English
+//! until translations ship.
+
+use super::ErrorCode;
+use crate::services::key_management::yubikey::domain::errors::ErrorCategory;
+use serde::{Deserialize, Serialize};
+
+use DiagnosticCommand as D;
+
+/// Locales the knowledge base has entries for
+pub const HELP_LOCALES: [&str; 1] = ["en"];
+
+/// Commands that help diagnose an error, referenced by command ID
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+#[serde(rename_all = "snake_case")]
+pub enum DiagnosticCommand {
+    GetDiagnostics,
+    GetFeatureFlags,
+    GetStorageUsage,
+    GetStoragePaths,
+    GetSecureDeleteCapability,
+    CheckDecryptionKey,
+    AssessSalvage,
+    VerifyToolIndependence,
+    GetCompatibilityChanges,
+    ListYubikeys,
+    GetPluginProtocolInfo,
+    GetPtySessions,
+}
+
+impl DiagnosticCommand {
+    pub const ALL: [Self; 12] = [
+        Self::GetDiagnostics,
+        Self::GetFeatureFlags,
+        Self::GetStorageUsage,
+        Self::GetStoragePaths,
+        Self::GetSecureDeleteCapability,
+        Self::CheckDecryptionKey,
+        Self::AssessSalvage,
+        Self::VerifyToolIndependence,
+        Self::GetCompatibilityChanges,
+        Self::ListYubikeys,
+        Self::GetPluginProtocolInfo,
+        Self::GetPtySessions,
+    ];
+
+    /// Name the command is invoked by
+    pub fn command_id(&self) -> &'static str {
+        match self {
+            Self::GetDiagnostics => "get_diagnostics",
+            Self::GetFeatureFlags => "get_feature_flags",
+            Self::GetStorageUsage => "get_storage_usage",
+            Self::GetStoragePaths => "get_storage_paths",
+            Self::GetSecureDeleteCapability => "get_secure_delete_capability",
+            Self::CheckDecryptionKey => "check_decryption_key",
+            Self::AssessSalvage => "assess_salvage",
+            Self::VerifyToolIndependence => "verify_tool_independence",
+            Self::GetCompatibilityChanges => "get_compatibility_changes",
+            Self::ListYubikeys => "list_yubikeys",
+            Self::GetPluginProtocolInfo => "get_plugin_protocol_info",
+            Self::GetPtySessions => "get_pty_sessions",
+        }
+    }
+}
+
+/// Details the error carried, used to make the steps specific
+#[derive(Debug, Clone, Default, Deserialize, specta::Type)]
+pub struct ErrorHelpContext {
+    pub path: Option<String>,
+    pub serial: Option<String>,
+    pub vault_name: Option<String>,
+    pub key_label: Option<String>,
+}
+
+/// Help for one error, ready to display
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, specta::Type)]
+pub struct ErrorHelp {
+    /// Locale the text is in; English when the requested one isn't available
+    pub locale: String,
+    pub title: String,
+    pub likely_causes: Vec<String>,
+    pub steps: Vec<String>,
+    pub diagnostics: Vec<DiagnosticCommand>,
+    /// Whether the user can resolve this without a new app version or support
+    pub user_fixable: bool,
+}
+
+struct HelpEntry {
+    title: &'static str,
+    causes: &'static [&'static str],
+    steps: &'static [&'static str],
+    diagnostics: &'static [DiagnosticCommand],
+}
+
+/// Whether the knowledge base can help with `code`
+pub fn has_error_help(code: &ErrorCode) -> bool {
+    !error_code_entry(code).steps.is_empty()
+}
+
+/// Help for `code`, made more specific by a YubiKey failure category
+pub fn error_help(
+    code: &ErrorCode,
+    yubikey_category: Option<&ErrorCategory>,
+    context: &ErrorHelpContext,
+    locale: Option<&str>,
+) -> ErrorHelp {
+    let (entry, user_fixable) = match yubikey_category {
+        Some(category) => (yubikey_entry(category), yubikey_fixable(category)),
+        None => (
+            error_code_entry(code),
+            super::error_recovery::get_recovery_guidance(code).1,
+        ),
+    };
+    ErrorHelp {
+        locale: help_locale(locale).to_string(),
+        title: entry.title.to_string(),
+        likely_causes: entry.causes.iter().map(|c| fill(c, context)).collect(),
+        steps: entry.steps.iter().map(|s| fill(s, context)).collect(),
+        diagnostics: entry.diagnostics.to_vec(),
+        user_fixable,
+    }
+}
+
+/// Best available locale for a request like `en-GB`
+fn help_locale(requested: Option<&str>) -> &'static str {
+    let language = requested
+        .and_then(|locale| locale.split(['-', '_']).next())
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+    HELP_LOCALES
+        .iter()
+        .copied()
+        .find(|locale| *locale == language)
+        .unwrap_or(HELP_LOCALES[0])
+}
+
+fn fill(text: &str, context: &ErrorHelpContext) -> String {
+    let quoted = |value: &Option<String>, fallback: &str| {
+        value
+            .as_deref()
+            .map_or_else(|| fallback.to_string(), |v| format!("'{v}'"))
+    };
+    let filled = text
+        .replace("{path}", &quoted(&context.path, "the file or folder"))
+        .replace(
+            "{serial}",
+            &context
+                .serial
+                .as_deref()
+                .map_or_else(|| "your YubiKey".to_string(), |s| format!("YubiKey {s}")),
+        )
+        .replace("{vault}", &quoted(&context.vault_name, "the vault"))
+        .replace("{key}", &quoted(&context.key_label, "the key"));
+
+    // A placeholder may have started the sentence
+    let mut chars = filled.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => filled,
+    }
+}
+
+const REPORT_STEP: &str = "If it keeps happening, report it on GitHub with the app version from Diagnostics and the steps that led to it";
+
+fn error_code_entry(code: &ErrorCode) -> HelpEntry {
+    match code {
+        // Validation errors
+        ErrorCode::InvalidInput => HelpEntry {
+            title: "Some input isn't valid",
+            causes: &["A field is empty, too long or in the wrong format"],
+            steps: &[
+                "Check each field for typos and stray spaces",
+                "Try the action again",
+            ],
+            diagnostics: &[],
+        },
+        ErrorCode::MissingParameter => HelpEntry {
+            title: "Required information is missing",
+            causes: &["A required field was left empty"],
+            steps: &["Fill in every required field", "Try the action again"],
+            diagnostics: &[],
+        },
+        ErrorCode::InvalidPath => HelpEntry {
+            title: "The path isn't valid",
+            causes: &[
+                "{path} contains characters the operating system doesn't allow",
+                "The path was typed rather than browsed to",
+            ],
+            steps: &[
+                "Use Browse to select the file or folder instead of typing its path",
+                "Check the spelling of {path}",
+            ],
+            diagnostics: &[],
+        },
+        ErrorCode::InvalidKeyLabel => HelpEntry {
+            title: "The key label isn't allowed",
+            causes: &["The label uses characters other than letters, numbers and dashes"],
+            steps: &["Rename {key} using only letters, numbers and dashes"],
+            diagnostics: &[],
+        },
+        ErrorCode::WeakPassphrase => HelpEntry {
+            title: "The passphrase is too weak",
+            causes: &["The passphrase is short or easy to guess"],
+            steps: &[
+                "Use at least 12 characters, or several unrelated words",
+                "Write the passphrase down and store it somewhere safe",
+            ],
+            diagnostics: &[],
+        },
+        ErrorCode::InvalidFileFormat => HelpEntry {
+            title: "The file isn't in a format Barqly Vault can read",
+            causes: &[
+                "{path} isn't a Barqly Vault archive or key file",
+                "The file was damaged while copying",
+            ],
+            steps: &[
+                "Check you selected the right file",
+                "Copy the file again from its original location",
+            ],
+            diagnostics: &[D::AssessSalvage],
+        },
+        ErrorCode::FileTooLarge => HelpEntry {
+            title: "The file is too large",
+            causes: &["{path} is larger than this operation allows"],
+            steps: &[
+                "Encrypt large files on their own, or split them into smaller parts",
+                "Check free space before trying again",
+            ],
+            diagnostics: &[D::GetStorageUsage],
+        },
+        ErrorCode::TooManyFiles => HelpEntry {
+            title: "Too many files are selected",
+            causes: &["The selection has more files than one archive allows"],
+            steps: &["Select fewer files, or encrypt them in smaller batches"],
+            diagnostics: &[],
+        },
+        ErrorCode::SelectionOverlapsAppData => HelpEntry {
+            title: "The selection includes Barqly Vault's own data",
+            causes: &["{path} contains the app's data, vaults folder or the archive being written"],
+            steps: &[
+                "Select a folder that doesn't contain Barqly Vault's data",
+                "Or turn off strict mode to leave the app's data out automatically",
+            ],
+            diagnostics: &[D::GetStoragePaths],
+        },
+        ErrorCode::ValidationFailed => HelpEntry {
+            title: "A field needs correcting",
+            causes: &["The highlighted field breaks one of its rules"],
+            steps: &["Correct the highlighted field", "Try the action again"],
+            diagnostics: &[],
+        },
+
+        // Permission errors
+        ErrorCode::PermissionDenied => HelpEntry {
+            title: "Permission denied",
+            causes: &[
+                "Your user account can't read or write {path}",
+                "Another program has the file locked",
+            ],
+            steps: &[
+                "Check the permissions of {path}",
+                "Close programs that may have it open",
+                "Choose a location inside your home folder",
+            ],
+            diagnostics: &[D::GetStoragePaths],
+        },
+        ErrorCode::PathNotAllowed => HelpEntry {
+            title: "That location isn't allowed",
+            causes: &["{path} is a system location the app won't read or write"],
+            steps: &["Choose a location in Documents, Desktop or another folder you own"],
+            diagnostics: &[],
+        },
+        ErrorCode::InsufficientPermissions => HelpEntry {
+            title: "The app doesn't have the access it needs",
+            causes: &["The operating system blocked access to {path}"],
+            steps: &[
+                "On macOS, allow Barqly Vault under Privacy & Security > Files and Folders",
+                "Otherwise choose a location your account can write to",
+            ],
+            diagnostics: &[D::GetStoragePaths],
+        },
+        ErrorCode::ReadOnlyFileSystem => HelpEntry {
+            title: "The destination is read-only",
+            causes: &[
+                "{path} is on a read-only or write-protected drive",
+                "The drive was mounted read-only after an error",
+            ],
+            steps: &[
+                "Check the drive's write-protect switch",
+                "Choose a writable location",
+            ],
+            diagnostics: &[D::GetStoragePaths],
+        },
+
+        // Not found errors
+        ErrorCode::KeyNotFound => HelpEntry {
+            title: "The key can't be found",
+            causes: &[
+                "{key} was deleted or its key file was moved",
+                "The key belongs to another vault",
+            ],
+            steps: &[
+                "Check {key} is listed under Keys",
+                "If the key file moved, relink it from the key menu",
+                "Restore the key from its backup if it was deleted",
+            ],
+            diagnostics: &[D::CheckDecryptionKey],
+        },
+        ErrorCode::KeyMediaNotPresent => HelpEntry {
+            title: "The drive holding the key isn't connected",
+            causes: &["{key} is stored on removable media that isn't inserted"],
+            steps: &[
+                "Insert the drive holding {key}",
+                "If the key moved, relink it from the key menu",
+            ],
+            diagnostics: &[],
+        },
+        ErrorCode::FileNotFound => HelpEntry {
+            title: "The file can't be found",
+            causes: &[
+                "{path} was moved, renamed or deleted",
+                "The drive it's on isn't connected",
+            ],
+            steps: &[
+                "Check {path} still exists",
+                "Reconnect the drive it's on",
+                "Browse to the file again",
+            ],
+            diagnostics: &[],
+        },
+        ErrorCode::DirectoryNotFound => HelpEntry {
+            title: "The folder can't be found",
+            causes: &["{path} was moved, renamed or deleted"],
+            steps: &[
+                "Check {path} still exists",
+                "Create the folder or choose another one",
+            ],
+            diagnostics: &[D::GetStoragePaths],
+        },
+        ErrorCode::OperationNotFound => HelpEntry {
+            title: "The operation is no longer running",
+            causes: &["The operation already finished or was cancelled"],
+            steps: &["Refresh the view", "Start the operation again if needed"],
+            diagnostics: &[D::GetPtySessions],
+        },
+
+        // Operation errors
+        ErrorCode::EncryptionFailed => HelpEntry {
+            title: "Encryption failed",
+            causes: &[
+                "A selected file couldn't be read",
+                "The vault's keys couldn't be loaded",
+                "There wasn't enough free space",
+            ],
+            steps: &[
+                "Close programs that have the selected files open",
+                "Check {vault} has at least one key",
+                "Free up space and try again",
+            ],
+            diagnostics: &[D::GetStorageUsage, D::GetDiagnostics],
+        },
+        ErrorCode::DecryptionFailed => HelpEntry {
+            title: "Decryption failed",
+            causes: &[
+                "The key or passphrase doesn't match the archive",
+                "The archive is damaged",
+            ],
+            steps: &[
+                "Check which keys can open the archive",
+                "Re-enter the passphrase or PIN carefully",
+                "If the archive is damaged, check how much can be recovered",
+            ],
+            diagnostics: &[D::CheckDecryptionKey, D::AssessSalvage],
+        },
+        ErrorCode::StorageFailed => HelpEntry {
+            title: "Saving failed",
+            causes: &["The disk is full, or the destination isn't writable"],
+            steps: &[
+                "Free up space on the destination drive",
+                "Choose another destination",
+            ],
+            diagnostics: &[D::GetStorageUsage, D::GetStoragePaths],
+        },
+        ErrorCode::ArchiveCorrupted => HelpEntry {
+            title: "The archive is damaged",
+            causes: &[
+                "{path} was cut short while copying or downloading",
+                "The storage it's on has failing sectors",
+            ],
+            steps: &[
+                "Try another copy of the archive",
+                "Check how much of the archive can be recovered",
+                "Re-encrypt the original files if you still have them",
+            ],
+            diagnostics: &[D::AssessSalvage, D::VerifyToolIndependence],
+        },
+        ErrorCode::ManifestInvalid => HelpEntry {
+            title: "The archive's file list is unreadable",
+            causes: &["The manifest of {vault} is damaged or from an incompatible version"],
+            steps: &[
+                "Try another copy of the archive",
+                "Regenerate the external manifest from the archive",
+            ],
+            diagnostics: &[D::GetCompatibilityChanges, D::AssessSalvage],
+        },
+        ErrorCode::IntegrityCheckFailed => HelpEntry {
+            title: "Files didn't pass verification",
+            causes: &[
+                "The archive was modified or damaged after it was created",
+                "Files changed while they were being encrypted",
+            ],
+            steps: &[
+                "Use a copy of the archive from a trusted location",
+                "Re-encrypt the original files",
+            ],
+            diagnostics: &[D::AssessSalvage, D::VerifyToolIndependence],
+        },
+        ErrorCode::ConcurrentOperation => HelpEntry {
+            title: "Another operation is running",
+            causes: &["An encryption, decryption or maintenance task is still in progress"],
+            steps: &["Wait for the current operation to finish", "Try again"],
+            diagnostics: &[D::GetPtySessions],
+        },
+
+        // Resource errors
+        ErrorCode::DiskSpaceInsufficient => HelpEntry {
+            title: "Not enough disk space",
+            causes: &["The drive needs room for the files plus the archive being written"],
+            steps: &[
+                "Free up space, or choose a drive with more room",
+                "Clear old decrypted output folders",
+            ],
+            diagnostics: &[D::GetStorageUsage],
+        },
+        ErrorCode::MemoryInsufficient => HelpEntry {
+            title: "Not enough memory",
+            causes: &["The operation needs more memory than is free"],
+            steps: &[
+                "Close other applications",
+                "Work with fewer or smaller files at a time",
+            ],
+            diagnostics: &[D::GetDiagnostics],
+        },
+        ErrorCode::FileSystemError => HelpEntry {
+            title: "The file system reported an error",
+            causes: &[
+                "The drive holding {path} was disconnected or has errors",
+                "The file system doesn't support an operation the app needs",
+            ],
+            steps: &[
+                "Reconnect the drive and try again",
+                "Check the drive with your system's disk utility",
+                "Try a different drive",
+            ],
+            diagnostics: &[D::GetSecureDeleteCapability, D::GetStoragePaths],
+        },
+        ErrorCode::NetworkError => HelpEntry {
+            title: "A network location couldn't be reached",
+            causes: &["{path} is on a network drive that disconnected"],
+            steps: &[
+                "Reconnect the network drive",
+                "Copy the files to a local drive and try again",
+            ],
+            diagnostics: &[],
+        },
+
+        // Security errors
+        ErrorCode::InvalidKey => HelpEntry {
+            title: "That key can't be used here",
+            causes: &[
+                "{key} isn't one of the keys the archive was encrypted to",
+                "The key file is damaged",
+            ],
+            steps: &[
+                "Check which keys can open the archive",
+                "Select a key registered with {vault}",
+            ],
+            diagnostics: &[D::CheckDecryptionKey],
+        },
+        ErrorCode::WrongPassphrase => HelpEntry {
+            title: "Wrong passphrase",
+            causes: &[
+                "The passphrase was mistyped or Caps Lock is on",
+                "This passphrase belongs to a different key",
+            ],
+            steps: &[
+                "Check Caps Lock and the keyboard layout",
+                "Re-enter the passphrase for {key}",
+            ],
+            diagnostics: &[D::CheckDecryptionKey],
+        },
+        ErrorCode::TamperedData => HelpEntry {
+            title: "The data has been modified",
+            causes: &["{path} changed after it was signed or encrypted"],
+            steps: &[
+                "Don't trust this copy",
+                "Use a copy from a trusted location",
+            ],
+            diagnostics: &[D::VerifyToolIndependence],
+        },
+        ErrorCode::UnauthorizedAccess => HelpEntry {
+            title: "Access isn't authorized",
+            causes: &["Your account isn't allowed to access {path}"],
+            steps: &[
+                "Check you're signed in to the right account",
+                "Ask whoever manages this computer for access",
+            ],
+            diagnostics: &[],
+        },
+
+        // YubiKey hardware errors
+        ErrorCode::YubiKeyError => HelpEntry {
+            title: "The YubiKey operation failed",
+            causes: &["{serial} was removed or didn't respond"],
+            steps: &[
+                "Reconnect {serial}",
+                "Close other apps that use the YubiKey",
+                "Try again",
+            ],
+            diagnostics: &[D::ListYubikeys, D::GetPluginProtocolInfo],
+        },
+        ErrorCode::YubiKeyNotFound => HelpEntry {
+            title: "No YubiKey detected",
+            causes: &[
+                "{serial} isn't inserted",
+                "The USB port or reader isn't working",
+            ],
+            steps: &["Insert {serial}", "Try another USB port or reader"],
+            diagnostics: &[D::ListYubikeys],
+        },
+        ErrorCode::YubiKeyPinRequired => HelpEntry {
+            title: "The YubiKey PIN is needed",
+            causes: &["The operation needs the PIN of {serial}"],
+            steps: &["Enter the 6-8 digit PIN you set for {serial}"],
+            diagnostics: &[],
+        },
+        ErrorCode::YubiKeyPinBlocked => HelpEntry {
+            title: "The YubiKey PIN is blocked",
+            causes: &["The PIN of {serial} was entered wrongly too many times"],
+            steps: &[
+                "Unblock the PIN with the PUK using YubiKey Manager",
+                "If the PUK is also blocked, use another key for {vault}",
+            ],
+            diagnostics: &[D::ListYubikeys],
+        },
+        ErrorCode::YubiKeyLastPinAttempt => HelpEntry {
+            title: "One PIN attempt left",
+            causes: &["Earlier wrong PINs left {serial} one try before the PIN blocks"],
+            steps: &[
+                "Check the PIN carefully, then confirm to use the last attempt",
+                "If unsure, reset the PIN with the PUK using YubiKey Manager first",
+            ],
+            diagnostics: &[D::ListYubikeys],
+        },
+        ErrorCode::YubiKeyTouchRequired => HelpEntry {
+            title: "Touch the YubiKey",
+            causes: &["{serial} is waiting for a touch to confirm"],
+            steps: &["Touch the contact on {serial} when it blinks"],
+            diagnostics: &[],
+        },
+        ErrorCode::YubiKeyTouchTimeout => HelpEntry {
+            title: "The YubiKey wasn't touched in time",
+            causes: &["{serial} stopped waiting for a touch"],
+            steps: &[
+                "Start the operation again",
+                "Touch {serial} as soon as it blinks",
+            ],
+            diagnostics: &[D::GetPtySessions],
+        },
+        ErrorCode::WrongYubiKey => HelpEntry {
+            title: "That's not the right YubiKey",
+            causes: &["The connected YubiKey isn't one registered with {vault}"],
+            steps: &[
+                "Insert {serial}",
+                "Or unlock with another key registered with {vault}",
+            ],
+            diagnostics: &[D::ListYubikeys, D::CheckDecryptionKey],
+        },
+        ErrorCode::YubiKeySlotInUse => HelpEntry {
+            title: "The YubiKey slot is in use",
+            causes: &["The selected slot on {serial} already holds a key"],
+            steps: &["Choose a different slot, or use another YubiKey"],
+            diagnostics: &[D::ListYubikeys],
+        },
+        ErrorCode::YubiKeyInitializationFailed => HelpEntry {
+            title: "YubiKey setup failed",
+            causes: &[
+                "{serial} was removed during setup",
+                "The age-plugin-yubikey plugin couldn't run",
+            ],
+            steps: &[
+                "Reconnect {serial} and try again",
+                "Check the plugin version",
+                "Reset the PIV applet with YubiKey Manager if setup keeps failing",
+            ],
+            diagnostics: &[D::ListYubikeys, D::GetPluginProtocolInfo],
+        },
+        ErrorCode::YubiKeyCommunicationError => HelpEntry {
+            title: "The YubiKey stopped responding",
+            causes: &["{serial} was disconnected or another app is using it"],
+            steps: &[
+                "Close other apps that use the YubiKey",
+                "Reconnect {serial}",
+            ],
+            diagnostics: &[D::ListYubikeys, D::GetPtySessions],
+        },
+
+        // Vault errors
+        ErrorCode::VaultNotFound => HelpEntry {
+            title: "The vault can't be found",
+            causes: &["{vault} was deleted or its data folder moved"],
+            steps: &[
+                "Check {vault} is in the vault list",
+                "Check the app's data folder is where it was",
+            ],
+            diagnostics: &[D::GetStoragePaths],
+        },
+        ErrorCode::VaultAlreadyExists => HelpEntry {
+            title: "A vault with that name exists",
+            causes: &["{vault} is already taken"],
+            steps: &["Choose a different vault name"],
+            diagnostics: &[],
+        },
+        ErrorCode::VaultKeyLimitExceeded => HelpEntry {
+            title: "The vault has the most keys it can hold",
+            causes: &["{vault} is at its key limit"],
+            steps: &["Remove a key you no longer use before adding another"],
+            diagnostics: &[],
+        },
+        ErrorCode::ArchiveImmutable => HelpEntry {
+            title: "The archive is locked against changes",
+            causes: &["The archive was marked immutable"],
+            steps: &[
+                "Clear the archive's immutable flag if you mean to replace it",
+                "Or encrypt to a different vault",
+            ],
+            diagnostics: &[],
+        },
+        ErrorCode::CrossVaultNameCollision => HelpEntry {
+            title: "Another vault's archive has that name",
+            causes: &["Vaults writing to the same folder named their archives alike"],
+            steps: &[
+                "Give {vault} an archive name template that includes its name",
+                "Or let the app add the vault's name to clashing archives",
+                "Or choose a different output folder",
+            ],
+            diagnostics: &[],
+        },
+        ErrorCode::StoreInUse => HelpEntry {
+            title: "Another account is using the shared vaults",
+            causes: &[
+                "Someone signed in to another account on this computer is changing a shared vault",
+                "The app closed unexpectedly in another account and left the store locked",
+            ],
+            steps: &[
+                "Wait for them to finish, then try again",
+                "If they're not using the app, take over the store from the shared store settings",
+            ],
+            diagnostics: &[],
+        },
+        ErrorCode::VaultConflictSuspected => HelpEntry {
+            title: "Another computer changed this vault",
+            causes: &[
+                "The vault is synced to another computer that changed it at the same time",
+                "A sync service kept both computers' copies of the vault's files",
+            ],
+            steps: &[
+                "Let the sync service finish on both computers",
+                "Resolve the conflict from {vault}'s menu, keeping this computer's copy, the other's, or both archive lists",
+            ],
+            diagnostics: &[],
+        },
+
+        // Key management errors
+        ErrorCode::KeyAlreadyExists => HelpEntry {
+            title: "The key already exists",
+            causes: &["{key} is already registered"],
+            steps: &["Use the existing key, or choose a different label"],
+            diagnostics: &[],
+        },
+        ErrorCode::InvalidKeyState => HelpEntry {
+            title: "The key can't be used in its current state",
+            causes: &["{key} is deactivated or still being set up"],
+            steps: &[
+                "Check the status of {key} under Keys",
+                "Reactivate or finish setting up the key",
+            ],
+            diagnostics: &[],
+        },
+        ErrorCode::KeyRemovalUnconfirmed => HelpEntry {
+            title: "Removing the key would lose archives",
+            causes: &[
+                "Some archives can only be opened with {key}",
+                "An archive was encrypted since the removal plan was shown",
+            ],
+            steps: &[
+                "Review which archives would become unreadable",
+                "Re-encrypt them to another key, or confirm the removal from the plan",
+            ],
+            diagnostics: &[],
+        },
+
+        // Plugin errors
+        ErrorCode::PluginNotFound => HelpEntry {
+            title: "The YubiKey plugin is missing",
+            causes: &["age-plugin-yubikey was removed or quarantined"],
+            steps: &[
+                "Restart the app to reinstall the bundled plugin",
+                "Reinstall Barqly Vault if it's still missing",
+            ],
+            diagnostics: &[D::GetPluginProtocolInfo],
+        },
+        ErrorCode::PluginVersionMismatch => HelpEntry {
+            title: "The YubiKey plugin version isn't supported",
+            causes: &["A different age-plugin-yubikey is installed than the app supports"],
+            steps: &[
+                "Check the installed plugin version",
+                "Update Barqly Vault, or reinstall it to restore the bundled plugin",
+            ],
+            diagnostics: &[D::GetPluginProtocolInfo],
+        },
+        ErrorCode::PluginExecutionFailed => HelpEntry {
+            title: "The YubiKey plugin failed",
+            causes: &["age-plugin-yubikey exited with an error or stalled"],
+            steps: &[
+                "Reconnect {serial}",
+                "Check the plugin version",
+                "Try again",
+            ],
+            diagnostics: &[D::GetPluginProtocolInfo, D::GetPtySessions],
+        },
+        ErrorCode::PluginDeploymentFailed => HelpEntry {
+            title: "The YubiKey plugin couldn't be installed",
+            causes: &["The app couldn't write the plugin to its data folder"],
+            steps: &[
+                "Check the app's data folder is writable",
+                "Reinstall Barqly Vault",
+            ],
+            diagnostics: &[D::GetStoragePaths, D::GetPluginProtocolInfo],
+        },
+
+        // Compatibility errors
+        ErrorCode::AppVersionTooOld => HelpEntry {
+            title: "This app is too old for the archive",
+            causes: &["The archive uses features from a newer Barqly Vault"],
+            steps: &["Install the latest Barqly Vault, then try again"],
+            diagnostics: &[D::GetCompatibilityChanges, D::GetDiagnostics],
+        },
+        ErrorCode::FeatureDisabled => HelpEntry {
+            title: "This feature isn't available",
+            causes: &["The feature is experimental or not part of this build"],
+            steps: &["Check which features this build has switched on"],
+            diagnostics: &[D::GetFeatureFlags],
+        },
+
+        // Multi-recipient errors
+        ErrorCode::NoUnlockMethodAvailable => HelpEntry {
+            title: "No way to unlock the vault is available",
+            causes: &["None of the keys registered with {vault} are present"],
+            steps: &[
+                "Insert a registered YubiKey, or select a passphrase key",
+                "Recover with recovery shares if you set them up",
+            ],
+            diagnostics: &[D::CheckDecryptionKey, D::ListYubikeys],
+        },
+        ErrorCode::RecipientMismatch => HelpEntry {
+            title: "The key isn't one the archive was encrypted to",
+            causes: &["{key} isn't among the archive's recipients"],
+            steps: &[
+                "Check which keys can open the archive",
+                "Use one of those keys",
+            ],
+            diagnostics: &[D::CheckDecryptionKey],
+        },
+        ErrorCode::MultiRecipientSetupFailed => HelpEntry {
+            title: "Setting up the vault's keys failed",
+            causes: &["One of the keys couldn't be added to {vault}"],
+            steps: &["Add the keys one at a time to find the one that fails"],
+            diagnostics: &[D::ListYubikeys],
+        },
+
+        // Internal errors
+        ErrorCode::InternalError => HelpEntry {
+            title: "Something went wrong inside the app",
+            causes: &["An unexpected condition in Barqly Vault"],
+            steps: &["Restart the app and try again", REPORT_STEP],
+            diagnostics: &[D::GetDiagnostics],
+        },
+        ErrorCode::UnexpectedError => HelpEntry {
+            title: "An unexpected error occurred",
+            causes: &["An unexpected condition in Barqly Vault"],
+            steps: &["Restart the app and try again", REPORT_STEP],
+            diagnostics: &[D::GetDiagnostics],
+        },
+        ErrorCode::ConfigurationError => HelpEntry {
+            title: "The app's settings are damaged",
+            causes: &["A settings file couldn't be read"],
+            steps: &[
+                "Restart the app",
+                "Check the app's data folder is intact",
+                REPORT_STEP,
+            ],
+            diagnostics: &[D::GetStoragePaths, D::GetDiagnostics],
+        },
+        ErrorCode::UnknownError => HelpEntry {
+            title: "An unknown error occurred",
+            causes: &["The error wasn't recognized"],
+            steps: &["Restart the app and try again", REPORT_STEP],
+            diagnostics: &[D::GetDiagnostics],
+        },
+    }
+}
+
+fn yubikey_entry(category: &ErrorCategory) -> HelpEntry {
+    match category {
+        ErrorCategory::Device => HelpEntry {
+            title: "The YubiKey or its reader wasn't found",
+            causes: &[
+                "{serial} isn't connected, or is in a different reader",
+                "More than one YubiKey is connected",
+            ],
+            steps: &["Connect only {serial}", "Try another USB port or reader"],
+            diagnostics: &[D::ListYubikeys],
+        },
+        ErrorCategory::Identity => HelpEntry {
+            title: "The YubiKey's identity couldn't be read",
+            causes: &["{serial} hasn't been set up with Barqly Vault, or was reset"],
+            steps: &[
+                "Check {serial} is listed with an identity",
+                "Set it up again if it was reset",
+            ],
+            diagnostics: &[D::ListYubikeys],
+        },
+        ErrorCategory::Registry => HelpEntry {
+            title: "The YubiKey isn't registered as expected",
+            causes: &["{serial} is missing from the key registry, or registered twice"],
+            steps: &[
+                "Check {serial} under Keys",
+                "Register the YubiKey with {vault} again",
+            ],
+            diagnostics: &[D::ListYubikeys],
+        },
+        ErrorCategory::File => HelpEntry {
+            title: "The YubiKey couldn't encrypt or decrypt the file",
+            causes: &["{path} isn't encrypted to {serial}, or couldn't be read"],
+            steps: &[
+                "Check which keys can open the archive",
+                "Check {path} is readable",
+            ],
+            diagnostics: &[D::CheckDecryptionKey],
+        },
+        ErrorCategory::Pin => HelpEntry {
+            title: "The YubiKey PIN wasn't accepted",
+            causes: &[
+                "The PIN of {serial} was mistyped",
+                "The PIN is blocked after too many attempts",
+            ],
+            steps: &[
+                "Re-enter the PIN carefully; each wrong try counts",
+                "If it's blocked, unblock it with the PUK in YubiKey Manager",
+            ],
+            diagnostics: &[D::ListYubikeys],
+        },
+        ErrorCategory::State => HelpEntry {
+            title: "The YubiKey isn't ready for this",
+            causes: &["{serial} is in the middle of setup, or isn't set up yet"],
+            steps: &["Finish or restart setting up {serial}"],
+            diagnostics: &[D::ListYubikeys],
+        },
+        ErrorCategory::Slot => HelpEntry {
+            title: "The YubiKey slot isn't available",
+            causes: &["The selected slot on {serial} is in use or unsupported"],
+            steps: &["Choose another slot, or use a different YubiKey"],
+            diagnostics: &[D::ListYubikeys],
+        },
+        ErrorCategory::AgePlugin => HelpEntry {
+            title: "The YubiKey plugin failed",
+            causes: &[
+                "age-plugin-yubikey is missing or an unsupported version",
+                "The plugin stalled waiting for the device",
+            ],
+            steps: &[
+                "Check the plugin version",
+                "Reinstall Barqly Vault to restore the bundled plugin",
+            ],
+            diagnostics: &[D::GetPluginProtocolInfo, D::GetPtySessions],
+        },
+        ErrorCategory::Serial => HelpEntry {
+            title: "The YubiKey serial isn't valid",
+            causes: &["The serial number is missing or malformed"],
+            steps: &["Select the YubiKey from the list instead of entering its serial"],
+            diagnostics: &[D::ListYubikeys],
+        },
+        ErrorCategory::Other => HelpEntry {
+            title: "The YubiKey operation failed",
+            causes: &["{serial} didn't complete the operation"],
+            steps: &["Reconnect {serial} and try again", REPORT_STEP],
+            diagnostics: &[D::ListYubikeys, D::GetDiagnostics],
+        },
+    }
+}
+
+fn yubikey_fixable(category: &ErrorCategory) -> bool {
+    !matches!(category, ErrorCategory::Other)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALL_CODES: [ErrorCode; 66] = [
+        ErrorCode::InvalidInput,
+        ErrorCode::MissingParameter,
+        ErrorCode::InvalidPath,
+        ErrorCode::InvalidKeyLabel,
+        ErrorCode::WeakPassphrase,
+        ErrorCode::InvalidFileFormat,
+        ErrorCode::FileTooLarge,
+        ErrorCode::TooManyFiles,
+        ErrorCode::SelectionOverlapsAppData,
+        ErrorCode::ValidationFailed,
+        ErrorCode::PermissionDenied,
+        ErrorCode::PathNotAllowed,
+        ErrorCode::InsufficientPermissions,
+        ErrorCode::ReadOnlyFileSystem,
+        ErrorCode::KeyNotFound,
+        ErrorCode::KeyMediaNotPresent,
+        ErrorCode::FileNotFound,
+        ErrorCode::DirectoryNotFound,
+        ErrorCode::OperationNotFound,
+        ErrorCode::EncryptionFailed,
+        ErrorCode::DecryptionFailed,
+        ErrorCode::StorageFailed,
+        ErrorCode::ArchiveCorrupted,
+        ErrorCode::ManifestInvalid,
+        ErrorCode::IntegrityCheckFailed,
+        ErrorCode::ConcurrentOperation,
+        ErrorCode::DiskSpaceInsufficient,
+        ErrorCode::MemoryInsufficient,
+        ErrorCode::FileSystemError,
+        ErrorCode::NetworkError,
+        ErrorCode::InvalidKey,
+        ErrorCode::WrongPassphrase,
+        ErrorCode::TamperedData,
+        ErrorCode::UnauthorizedAccess,
+        ErrorCode::YubiKeyError,
+        ErrorCode::YubiKeyNotFound,
+        ErrorCode::YubiKeyPinRequired,
+        ErrorCode::YubiKeyPinBlocked,
+        ErrorCode::YubiKeyLastPinAttempt,
+        ErrorCode::YubiKeyTouchRequired,
+        ErrorCode::YubiKeyTouchTimeout,
+        ErrorCode::WrongYubiKey,
+        ErrorCode::YubiKeySlotInUse,
+        ErrorCode::YubiKeyInitializationFailed,
+        ErrorCode::YubiKeyCommunicationError,
+        ErrorCode::VaultNotFound,
+        ErrorCode::VaultAlreadyExists,
+        ErrorCode::VaultKeyLimitExceeded,
+        ErrorCode::ArchiveImmutable,
+        ErrorCode::CrossVaultNameCollision,
+        ErrorCode::StoreInUse,
+        ErrorCode::VaultConflictSuspected,
+        ErrorCode::KeyAlreadyExists,
+        ErrorCode::InvalidKeyState,
+        ErrorCode::KeyRemovalUnconfirmed,
+        ErrorCode::PluginNotFound,
+        ErrorCode::PluginVersionMismatch,
+        ErrorCode::PluginExecutionFailed,
+        ErrorCode::PluginDeploymentFailed,
+        ErrorCode::AppVersionTooOld,
+        ErrorCode::FeatureDisabled,
+        ErrorCode::NoUnlockMethodAvailable,
+        ErrorCode::RecipientMismatch,
+        ErrorCode::MultiRecipientSetupFailed,
+        ErrorCode::InternalError,
+        ErrorCode::UnexpectedError,
+        ErrorCode::ConfigurationError,
+        ErrorCode::UnknownError,
+    ];
+
+    const ALL_CATEGORIES: [ErrorCategory; 10] = [
+        ErrorCategory::Device,
+        ErrorCategory::Identity,
+        ErrorCategory::Registry,
+        ErrorCategory::File,
+        ErrorCategory::Pin,
+        ErrorCategory::State,
+        ErrorCategory::Slot,
+        ErrorCategory::AgePlugin,
+        ErrorCategory::Serial,
+        ErrorCategory::Other,
+    ];
+
+    /// Variant names declared in `enum_name` in `source`
+    fn declared_variants(source: &str, enum_name: &str) -> Vec<String> {
+        let start = source
+            .find(&format!("pub enum {enum_name} {{"))
+            .expect("enum declared");
+        let body = &source[start..];
+        let body = &body[body.find('{').unwrap() + 1..body.find("\n}").unwrap()];
+        body.lines()
+            .map(str::trim)
+            .filter(|line| !line.starts_with("//") && !line.is_empty())
+            .filter_map(|line| line.strip_suffix(','))
+            .filter(|name| name.chars().all(char::is_alphanumeric))
+            .map(str::to_string)
+            .collect()
+    }
+
+    fn assert_complete(entry: &HelpEntry) {
+        assert!(!entry.title.is_empty());
+        assert!(!entry.causes.is_empty(), "{} has no causes", entry.title);
+        assert!(!entry.steps.is_empty(), "{} has no steps", entry.title);
+    }
+
+    #[test]
+    fn test_every_error_code_has_help() {
+        // The lists above must name every declared variant
+        let codes: Vec<String> = ALL_CODES.iter().map(|code| format!("{code:?}")).collect();
+        assert_eq!(
+            codes,
+            declared_variants(include_str!("error_code.rs"), "ErrorCode")
+        );
+        let categories: Vec<String> = ALL_CATEGORIES.iter().map(|c| format!("{c:?}")).collect();
+        assert_eq!(
+            categories,
+            declared_variants(
+                include_str!("../services/key_management/yubikey/domain/errors.rs"),
+                "ErrorCategory"
+            )
+        );
+
+        for code in &ALL_CODES {
+            assert_complete(&error_code_entry(code));
+            assert!(has_error_help(code), "{code:?}");
+        }
+        for category in &ALL_CATEGORIES {
+            assert_complete(&yubikey_entry(category));
+        }
+    }
+
+    #[test]
+    fn test_referenced_diagnostics_are_registered_commands() {
+        let lib = include_str!("../lib.rs");
+        let handler = &lib[lib.find("generate_handler![").expect("handler list")..];
+        let registered: Vec<&str> = handler
+            .split(|c: char| !(c.is_alphanumeric() || c == '_'))
+            .collect();
+
+        for diagnostic in DiagnosticCommand::ALL {
+            assert!(
+                registered.contains(&diagnostic.command_id()),
+                "{} isn't a registered command",
+                diagnostic.command_id()
+            );
+            // Serialized as the command ID the UI invokes
+            assert_eq!(
+                serde_json::to_value(diagnostic).unwrap(),
+                diagnostic.command_id()
+            );
+        }
+
+        let referenced = ALL_CODES
+            .iter()
+            .flat_map(|code| error_code_entry(code).diagnostics)
+            .chain(
+                ALL_CATEGORIES
+                    .iter()
+                    .flat_map(|c| yubikey_entry(c).diagnostics),
+            );
+        for diagnostic in referenced {
+            assert!(DiagnosticCommand::ALL.contains(diagnostic));
+        }
+    }
+
+    #[test]
+    fn test_help_is_tailored_to_the_error_context() {
+        let context = ErrorHelpContext {
+            path: Some("/home/ada/deeds.pdf".to_string()),
+            ..ErrorHelpContext::default()
+        };
+        let help = error_help(&ErrorCode::FileNotFound, None, &context, Some("en-GB"));
+        assert_eq!(help.locale, "en");
+        assert_eq!(help.steps[0], "Check '/home/ada/deeds.pdf' still exists");
+        assert!(help.user_fixable);
+
+        // Without context the steps stay generic
+        let help = error_help(
+            &ErrorCode::FileNotFound,
+            None,
+            &ErrorHelpContext::default(),
+            Some("fr"),
+        );
+        assert_eq!(help.locale, "en");
+        assert_eq!(help.steps[0], "Check the file or folder still exists");
+        assert_eq!(
+            help.likely_causes[0],
+            "The file or folder was moved, renamed or deleted"
+        );
+
+        // A YubiKey category gives the more specific entry
+        let context = ErrorHelpContext {
+            serial: Some("12345678".to_string()),
+            ..ErrorHelpContext::default()
+        };
+        let help = error_help(
+            &ErrorCode::YubiKeyError,
+            Some(&ErrorCategory::Pin),
+            &context,
+            None,
+        );
+        assert_eq!(help.title, "The YubiKey PIN wasn't accepted");
+        assert_eq!(
+            help.likely_causes[0],
+            "The PIN of YubiKey 12345678 was mistyped"
+        );
+        assert_eq!(help.diagnostics, vec![DiagnosticCommand::ListYubikeys]);
+    }
+}
@@ -39,6 +39,14 @@ pub fn get_recovery_guidance(code: &ErrorCode) -> (Option<String>, bool) {
             Some("Reduce the number of selected files, or encrypt them in smaller batches".to_string()),
             true,
         ),
+        ErrorCode::SelectionOverlapsAppData => (
+            Some("Select a folder that doesn't contain Barqly Vault's data or vaults folder, or turn off strict mode to leave them out".to_string()),
+            true,
+        ),
+        ErrorCode::ValidationFailed => (
+            Some("Correct the highlighted field and try again".to_string()),
+            true,
+        ),
 
         // Permission errors - user actionable
         ErrorCode::PermissionDenied => (
@@ -63,6 +71,10 @@ pub fn get_recovery_guidance(code: &ErrorCode) -> (Option<String>, bool) {
             Some("Generate a new key in the Setup tab, or check if the key file was moved or deleted".to_string()),
             true,
         ),
+        ErrorCode::KeyMediaNotPresent => (
+            Some("Insert the drive holding this key file and try again. If the key has moved, relink it from the key menu".to_string()),
+            true,
+        ),
         ErrorCode::FileNotFound => (
             Some("Verify the file still exists at the specified location, or browse to select it again".to_string()),
             true,
@@ -168,6 +180,10 @@ pub fn get_recovery_guidance(code: &ErrorCode) -> (Option<String>, bool) {
             Some("Your YubiKey PIN is blocked after too many incorrect attempts. Use your PUK (PIN Unblocking Key) to reset it".to_string()),
             true,
         ),
+        ErrorCode::YubiKeyLastPinAttempt => (
+            Some("Only one PIN attempt is left. Double-check the PIN before confirming: another wrong PIN blocks it until it's reset with the PUK".to_string()),
+            true,
+        ),
         ErrorCode::YubiKeyTouchRequired => (
             Some("Touch the gold contact on your YubiKey when it blinks or glows to confirm the operation".to_string()),
             true,
@@ -211,6 +227,16 @@ pub fn get_recovery_guidance(code: &ErrorCode) -> (Option<String>, bool) {
             true,
         ),
 
+        // Compatibility Errors - user actionable
+        ErrorCode::AppVersionTooOld => (
+            Some("This archive was created by a newer version of Barqly Vault. Download and install the latest version, then try again".to_string()),
+            true,
+        ),
+        ErrorCode::FeatureDisabled => (
+            Some("This feature isn't available in this version of Barqly Vault".to_string()),
+            false,
+        ),
+
         // Multi-recipient Errors - user actionable
         ErrorCode::NoUnlockMethodAvailable => (
             Some("No valid unlock methods are currently available. Connect your YubiKey or ensure you have the correct passphrase".to_string()),
@@ -244,6 +270,22 @@ pub fn get_recovery_guidance(code: &ErrorCode) -> (Option<String>, bool) {
             Some("Vault key limit exceeded. Each vault can have 1 passphrase and up to 3 YubiKeys".to_string()),
             true,
         ),
+        ErrorCode::ArchiveImmutable => (
+            Some("This archive is marked immutable. Clear the immutable flag in the archive list (you'll be asked to confirm), then try again".to_string()),
+            true,
+        ),
+        ErrorCode::CrossVaultNameCollision => (
+            Some("Another vault already has an archive of this name in the output folder. Change the vault's archive name template, choose another folder, or let the app add the vault's name to clashing archives".to_string()),
+            true,
+        ),
+        ErrorCode::StoreInUse => (
+            Some("Another account on this computer is changing the shared vaults. Try again when they're done, or take over the store if they've left the app".to_string()),
+            true,
+        ),
+        ErrorCode::VaultConflictSuspected => (
+            Some("This vault was changed on another computer at the same time. Resolve the conflict, keeping one computer's copy or merging both archive lists, then try again".to_string()),
+            true,
+        ),
 
         // Key Management errors
         ErrorCode::KeyAlreadyExists => (
@@ -254,6 +296,10 @@ pub fn get_recovery_guidance(code: &ErrorCode) -> (Option<String>, bool) {
             Some("Key is in a state that doesn't allow this operation. Check key lifecycle status".to_string()),
             true,
         ),
+        ErrorCode::KeyRemovalUnconfirmed => (
+            Some("Some archives can only be opened with this key. Review the removal plan, re-encrypt them to another key or confirm with the plan shown".to_string()),
+            true,
+        ),
         ErrorCode::UnknownError => (
             Some("An unknown error occurred. Please try again or contact support".to_string()),
             false,
@@ -17,8 +17,13 @@
 //! - Progress includes percentage, message, and operation-specific details
 //! - Frontend can subscribe to progress events for real-time updates
 //!
+//! ## Units
+//! - Byte counts are `ByteSize` and durations are `DurationMs` (milliseconds)
+//! - Both serialize as plain numbers; display strings go in `FormatHints`
+//!
 //! ## Security Considerations
 //! - Sensitive data (passphrases, keys) are never logged
+//! - Serials, recipients and key paths are wrapped in `Sensitive` outside the bindings
 //! - Error messages don't leak sensitive information
 //! - All input is validated before processing
 
@@ -26,16 +31,28 @@
 mod core;
 mod error;
 mod error_code;
+mod error_help;
 mod error_recovery;
 mod progress;
+mod sensitive;
+mod units;
 mod validation;
 
 // Re-export all types for backward compatibility
 pub use core::{CommandResponse, CommandResult, ProgressCallback};
 pub use error::CommandError;
 pub use error_code::ErrorCode;
-pub use progress::{ProgressDetails, ProgressUpdate, YubiKeyOperationType, YubiKeyPhase};
-pub use validation::{ValidateInput, ValidateInputDetailed, ValidationHelper};
+pub use error_help::{
+    DiagnosticCommand, ErrorHelp, ErrorHelpContext, HELP_LOCALES, error_help, has_error_help,
+};
+pub use progress::{
+    IoPriority, ProgressDetails, ProgressUpdate, YubiKeyOperationType, YubiKeyPhase,
+};
+pub use sensitive::{Redactable, Sensitive};
+pub use units::{ByteSize, DurationMs, FormatHints, format_byte_size, format_duration_ms};
+pub use validation::{
+    ValidateInput, ValidateInputDetailed, ValidationFailure, ValidationHelper, ValidationRule,
+};
 
 // Re-export infrastructure utilities for backward compatibility
 pub use crate::services::shared::infrastructure::error::ErrorHandler;
@@ -2,6 +2,7 @@
 //!
 //! This module defines types for reporting progress updates during operations.
 
+use super::units::{ByteSize, DurationMs};
 use serde::{Deserialize, Serialize};
 
 /// Progress update for streaming operations with detailed information
@@ -17,7 +18,9 @@
 ///   message: string;
 ///   details?: ProgressDetails;
 ///   timestamp: string; // ISO 8601
-///   estimated_time_remaining?: number; // seconds
+///   estimated_time_remaining?: number; // milliseconds
+///   io_priority: 'Normal' | 'Background';
+///   trace_id?: string;
 /// }
 /// ```
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -32,8 +35,26 @@ pub struct ProgressUpdate {
     pub details: Option<ProgressDetails>,
     /// Timestamp of the progress update
     pub timestamp: chrono::DateTime<chrono::Utc>,
-    /// Estimated time remaining in seconds
-    pub estimated_time_remaining: Option<u64>,
+    /// Estimated time remaining
+    pub estimated_time_remaining: Option<DurationMs>,
+    /// I/O priority the operation is running with, so the UI can explain
+    /// why a background operation is slower
+    #[serde(default)]
+    pub io_priority: IoPriority,
+    /// Trace ID of the command that started the operation
+    #[serde(default)]
+    pub trace_id: Option<String>,
+}
+
+/// How hard a long-running operation may use the disk
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+pub enum IoPriority {
+    /// Full speed
+    #[default]
+    Normal,
+    /// Reduced OS I/O priority, fewer workers and pauses between write
+    /// bursts, keeping other apps responsive
+    Background,
 }
 
 /// Operation-specific progress details for different command types
@@ -48,7 +69,9 @@ pub struct ProgressUpdate {
 ///   | { type: 'Encryption'; bytes_processed: number; total_bytes: number; encryption_rate?: number }
 ///   | { type: 'Decryption'; bytes_processed: number; total_bytes: number; decryption_rate?: number }
 ///   | { type: 'ArchiveOperation'; files_processed: number; total_files: number; bytes_processed: number; total_bytes: number; compression_ratio?: number }
-///   | { type: 'ManifestOperation'; files_verified: number; total_files: number; current_file: string };
+///   | { type: 'ManifestOperation'; files_verified: number; total_files: number; current_file: string }
+///   | { type: 'Maintenance'; vault_id: string; task: string; cells_completed: number; total_cells: number }
+///   | { type: 'BatchDecryption'; archive_id: string; archives_completed: number; total_archives: number; waiting_for_touch: boolean };
 /// ```
 #[derive(Debug, Serialize, Deserialize, Clone, specta::Type)]
 #[serde(tag = "type")]
@@ -62,25 +85,25 @@ pub enum ProgressDetails {
         /// Progress within current file (0.0 to 1.0)
         current_file_progress: f32,
         /// Size of current file in bytes
-        current_file_size: u64,
+        current_file_size: ByteSize,
         /// Total size of all files in bytes
-        total_size: u64,
+        total_size: ByteSize,
     },
     /// Encryption operation progress
     Encryption {
         /// Bytes processed so far
-        bytes_processed: u64,
+        bytes_processed: ByteSize,
         /// Total bytes to process
-        total_bytes: u64,
+        total_bytes: ByteSize,
         /// Encryption rate in bytes per second
         encryption_rate: Option<f64>,
     },
     /// Decryption operation progress
     Decryption {
         /// Bytes processed so far
-        bytes_processed: u64,
+        bytes_processed: ByteSize,
         /// Total bytes to process
-        total_bytes: u64,
+        total_bytes: ByteSize,
         /// Decryption rate in bytes per second
         decryption_rate: Option<f64>,
     },
@@ -91,9 +114,9 @@ pub enum ProgressDetails {
         /// Total files to process
         total_files: usize,
         /// Bytes processed so far
-        bytes_processed: u64,
+        bytes_processed: ByteSize,
         /// Total bytes to process
-        total_bytes: u64,
+        total_bytes: ByteSize,
         /// Compression ratio achieved
         compression_ratio: Option<f32>,
     },
@@ -117,6 +140,35 @@ pub enum ProgressDetails {
         /// Additional context information
         context: Option<String>,
     },
+    /// Bulk vault maintenance progress (one step per task per vault)
+    Maintenance {
+        /// Vault of the cell that just finished
+        vault_id: String,
+        /// Task of the cell that just finished
+        task: String,
+        /// Cells finished so far
+        cells_completed: usize,
+        /// Total cells in the run
+        total_cells: usize,
+    },
+    /// Multi-archive YubiKey decryption progress (one step per archive)
+    BatchDecryption {
+        /// Archive being decrypted, or the one that just finished
+        archive_id: String,
+        /// Archives finished so far
+        archives_completed: usize,
+        /// Total archives in the batch
+        total_archives: usize,
+        /// Whether the user is being asked to touch the YubiKey
+        waiting_for_touch: bool,
+    },
+    /// Parity sidecar generation for a finished archive
+    Parity {
+        /// Archive bytes processed so far
+        bytes_processed: ByteSize,
+        /// Archive size
+        total_bytes: ByteSize,
+    },
 }
 
 /// Types of YubiKey operations
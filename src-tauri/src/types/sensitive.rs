@@ -0,0 +1,154 @@
+//! Redaction of key material identifiers
+//!
+//! Serials, recipients, identity tags and key file paths aren't secrets on
+//! their own, but together they tie a person to their hardware and files.
+//! Fields holding them are wrapped in [`Sensitive`] so Debug, Display and
+//! serde output the redacted form; the raw value is only reachable through
+//! [`Sensitive::expose_for_bindings`], which is easy to grep for.
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// How a value looks when it must not be shown in full
+pub trait Redactable {
+    fn redacted(&self) -> String;
+}
+
+impl Redactable for str {
+    fn redacted(&self) -> String {
+        "[REDACTED]".to_string()
+    }
+}
+
+impl Redactable for String {
+    fn redacted(&self) -> String {
+        self.as_str().redacted()
+    }
+}
+
+/// Only the file name is kept; the directories say where the user keeps keys
+impl Redactable for Path {
+    fn redacted(&self) -> String {
+        match self.file_name() {
+            Some(name) => format!("[REDACTED]/{}", name.to_string_lossy()),
+            None => "[REDACTED]".to_string(),
+        }
+    }
+}
+
+impl Redactable for PathBuf {
+    fn redacted(&self) -> String {
+        self.as_path().redacted()
+    }
+}
+
+impl<T: Redactable> Redactable for Option<T> {
+    fn redacted(&self) -> String {
+        match self {
+            Some(value) => value.redacted(),
+            None => "None".to_string(),
+        }
+    }
+}
+
+impl<T: Redactable + ?Sized> Redactable for &T {
+    fn redacted(&self) -> String {
+        (**self).redacted()
+    }
+}
+
+/// A value that is redacted everywhere except [`Sensitive::expose_for_bindings`]
+///
+/// Deserializes from the raw value, so wrapping a field doesn't change what
+/// it reads.
+#[derive(Clone, PartialEq, Eq, Hash, Default)]
+pub struct Sensitive<T>(T);
+
+impl<T> Sensitive<T> {
+    pub fn new(value: T) -> Self {
+        Sensitive(value)
+    }
+
+    /// The raw value, for the places that genuinely need it: handing it to
+    /// an external tool or a binding the UI displays
+    pub fn expose_for_bindings(&self) -> &T {
+        &self.0
+    }
+
+    /// Consume and return the raw value
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> From<T> for Sensitive<T> {
+    fn from(value: T) -> Self {
+        Sensitive(value)
+    }
+}
+
+impl<T: Redactable> Redactable for Sensitive<T> {
+    fn redacted(&self) -> String {
+        self.0.redacted()
+    }
+}
+
+impl<T: Redactable> fmt::Debug for Sensitive<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0.redacted())
+    }
+}
+
+impl<T: Redactable> fmt::Display for Sensitive<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0.redacted())
+    }
+}
+
+impl<T: Redactable> Serialize for Sensitive<T> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&self.0.redacted())
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for Sensitive<T> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        T::deserialize(deserializer).map(Sensitive)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CANARY: &str = "age1yubikey1canary";
+
+    #[test]
+    fn test_every_output_is_redacted() {
+        let value = Sensitive::new(CANARY.to_string());
+
+        assert!(!format!("{value:?}").contains(CANARY));
+        assert!(!format!("{value}").contains(CANARY));
+        assert!(!serde_json::to_string(&value).unwrap().contains(CANARY));
+        assert_eq!(value.expose_for_bindings(), CANARY);
+    }
+
+    #[test]
+    fn test_deserializes_raw_value() {
+        let value: Sensitive<String> = serde_json::from_str(&format!("\"{CANARY}\"")).unwrap();
+        assert_eq!(value.into_inner(), CANARY);
+    }
+
+    #[test]
+    fn test_path_keeps_file_name_only() {
+        let path = Sensitive::new(PathBuf::from("/Volumes/KEYS/barqly/work.agekey.enc"));
+        assert_eq!(path.to_string(), "[REDACTED]/work.agekey.enc");
+    }
+}
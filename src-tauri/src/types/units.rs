@@ -0,0 +1,706 @@
+//! Typed units for sizes and durations in command responses
+//!
+//! Every byte count crossing the Tauri bridge is a [`ByteSize`] and every
+//! duration is a [`DurationMs`]. Both serialize as plain JSON numbers, so the
+//! wire format is unchanged, but the unit is carried by the type instead of
+//! the field name or a comment.
+//!
+//! Human-readable strings are never the primary value. Responses that want a
+//! display string attach an optional [`FormatHints`] block produced by the
+//! English formatters below, which share their policy with the localized
+//! ones in `services::shared::infrastructure::formatting`, so the frontend
+//! and the Rust side agree on formatting.
+
+use crate::services::shared::infrastructure::formatting::{Locale, format_bytes, format_duration};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::iter::Sum;
+use std::ops::{Add, AddAssign};
+use std::time::Duration;
+
+/// A size in bytes
+///
+/// Serialized as a plain number of bytes.
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Default,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+    specta::Type,
+)]
+#[serde(transparent)]
+pub struct ByteSize(pub u64);
+
+impl ByteSize {
+    pub const ZERO: Self = Self(0);
+
+    pub fn bytes(self) -> u64 {
+        self.0
+    }
+
+    /// Human-readable form, e.g. `1.5 kB`
+    pub fn display(self) -> String {
+        format_byte_size(self.0)
+    }
+}
+
+impl From<u64> for ByteSize {
+    fn from(bytes: u64) -> Self {
+        Self(bytes)
+    }
+}
+
+impl From<ByteSize> for u64 {
+    fn from(size: ByteSize) -> Self {
+        size.0
+    }
+}
+
+impl Add for ByteSize {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Self(self.0.saturating_add(rhs.0))
+    }
+}
+
+impl AddAssign for ByteSize {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+impl Sum for ByteSize {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::ZERO, Add::add)
+    }
+}
+
+impl fmt::Display for ByteSize {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.display())
+    }
+}
+
+/// A duration in milliseconds
+///
+/// Serialized as a plain number of milliseconds.
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Default,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+    specta::Type,
+)]
+#[serde(transparent)]
+pub struct DurationMs(pub u64);
+
+impl DurationMs {
+    pub const ZERO: Self = Self(0);
+
+    pub fn from_secs(secs: u64) -> Self {
+        Self(secs.saturating_mul(1000))
+    }
+
+    pub fn millis(self) -> u64 {
+        self.0
+    }
+
+    /// Human-readable form, e.g. `2 min 5 s`
+    pub fn display(self) -> String {
+        format_duration_ms(self.0)
+    }
+}
+
+impl From<u64> for DurationMs {
+    fn from(millis: u64) -> Self {
+        Self(millis)
+    }
+}
+
+impl From<Duration> for DurationMs {
+    fn from(duration: Duration) -> Self {
+        Self(u64::try_from(duration.as_millis()).unwrap_or(u64::MAX))
+    }
+}
+
+impl From<DurationMs> for Duration {
+    fn from(duration: DurationMs) -> Self {
+        Duration::from_millis(duration.0)
+    }
+}
+
+impl fmt::Display for DurationMs {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.display())
+    }
+}
+
+/// Optional display strings that accompany typed size/duration fields
+///
+/// Produced only by [`FormatHints::size`], [`FormatHints::duration`] and
+/// [`FormatHints::new`] so every response formats units the same way.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+pub struct FormatHints {
+    /// Formatted primary size of the response, e.g. `1.5 MB`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size: Option<String>,
+    /// Formatted primary duration of the response, e.g. `1 min 30 s`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duration: Option<String>,
+}
+
+impl FormatHints {
+    pub fn new(size: Option<ByteSize>, duration: Option<DurationMs>) -> Self {
+        Self {
+            size: size.map(ByteSize::display),
+            duration: duration.map(DurationMs::display),
+        }
+    }
+
+    pub fn size(size: ByteSize) -> Self {
+        Self::new(Some(size), None)
+    }
+
+    pub fn duration(duration: DurationMs) -> Self {
+        Self::new(None, Some(duration))
+    }
+}
+
+/// Format a byte count for display in English (`0 B`, `512 B`, `1.5 kB`)
+///
+/// See [`format_bytes`] for the size policy and other locales.
+pub fn format_byte_size(bytes: u64) -> String {
+    format_bytes(bytes, Locale::En)
+}
+
+/// Format milliseconds for display in English (`850 ms`, `1 min 30 s`)
+///
+/// See [`format_duration`] for other locales.
+pub fn format_duration_ms(millis: u64) -> String {
+    format_duration(Duration::from_millis(millis), Locale::En)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_serializes_as_plain_numbers() {
+        assert_eq!(serde_json::to_string(&ByteSize(1024)).unwrap(), "1024");
+        assert_eq!(serde_json::to_string(&DurationMs(250)).unwrap(), "250");
+        assert_eq!(
+            serde_json::from_str::<ByteSize>("2048").unwrap(),
+            ByteSize(2048)
+        );
+        assert_eq!(
+            serde_json::from_str::<Option<DurationMs>>("null").unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_format_byte_size() {
+        assert_eq!(format_byte_size(0), "0 B");
+        assert_eq!(format_byte_size(512), "512 B");
+        assert_eq!(format_byte_size(1000), "1.0 kB");
+        assert_eq!(format_byte_size(1500), "1.5 kB");
+        assert_eq!(format_byte_size(1_000_000), "1.0 MB");
+        assert_eq!(format_byte_size(2_500_000), "2.5 MB");
+        assert_eq!(format_byte_size(10_000_000), "10.0 MB");
+        assert_eq!(format_byte_size(1_000_000_000_000), "1.0 TB");
+    }
+
+    #[test]
+    fn test_format_duration_ms() {
+        assert_eq!(format_duration_ms(0), "0 ms");
+        assert_eq!(format_duration_ms(850), "850 ms");
+        assert_eq!(format_duration_ms(1500), "1.5 s");
+        assert_eq!(format_duration_ms(90_000), "1 min 30 s");
+        assert_eq!(format_duration_ms(3_720_000), "1 h 2 min");
+    }
+
+    #[test]
+    fn test_conversions_and_hints() {
+        assert_eq!(DurationMs::from(Duration::from_secs(2)), DurationMs(2000));
+        assert_eq!(DurationMs::from_secs(3), DurationMs(3000));
+        assert_eq!(
+            [ByteSize(1), ByteSize(2)].into_iter().sum::<ByteSize>(),
+            ByteSize(3)
+        );
+
+        let hints = FormatHints::new(Some(ByteSize(1536)), Some(DurationMs(90_000)));
+        assert_eq!(hints.size.as_deref(), Some("1.5 kB"));
+        assert_eq!(hints.duration.as_deref(), Some("1 min 30 s"));
+        assert_eq!(
+            serde_json::to_string(&FormatHints::size(ByteSize(0))).unwrap(),
+            r#"{"size":"0 B"}"#
+        );
+    }
+}
+
+/// Type-level checklist of size/duration fields in public responses
+///
+/// Each response is destructured without `..`, so adding a field to any of
+/// them fails to compile until it is listed here. Size and duration fields
+/// go through `typed`, which only accepts the unit types; everything else is
+/// explicitly ignored. Nothing here runs.
+#[cfg(test)]
+#[allow(dead_code)]
+mod response_checklist {
+    use super::{ByteSize, DurationMs};
+    use crate::commands::crypto::{
+        EncryptionStatusResponse, GetProgressResponse, VerifyManifestResponse,
+    };
+    use crate::commands::key_management::export_key::ExportKeyResponse;
+    use crate::services::crypto::application::services::{BatchArchiveResult, BrowseSessionInfo};
+    use crate::services::crypto::domain::models::{
+        BenchmarkResult, BenchmarkSizes, PhaseThroughput, PhaseTiming, TimingBreakdown,
+    };
+    use crate::services::file::domain::models::{
+        FileInfo, FileSelection, Manifest, UploadMetadata, UploadPartChecksum,
+    };
+    use crate::services::file::infrastructure::file_operations::{
+        ResilientSourceConfig, ResilientSourceReport,
+    };
+    use crate::services::key_management::yubikey::infrastructure::pty::PtySessionInfo;
+    use crate::services::vault::application::services::{GlobalVaultStatistics, VaultStatistics};
+    use crate::services::vault::domain::models::{
+        ArchiveContent, EncryptedArchive, MaintenanceCell, StatisticsRecord,
+    };
+    use crate::types::{ProgressDetails, ProgressUpdate};
+
+    trait TypedUnit {}
+    impl TypedUnit for ByteSize {}
+    impl TypedUnit for DurationMs {}
+    impl<T: TypedUnit> TypedUnit for Option<T> {}
+
+    fn typed<T: TypedUnit>(_: &T) {}
+
+    fn file_info(v: &FileInfo) {
+        let FileInfo {
+            path: _,
+            name: _,
+            size,
+            is_file: _,
+            is_directory: _,
+            file_count: _,
+        } = v;
+        typed(size);
+    }
+
+    fn file_selection(v: &FileSelection) {
+        let FileSelection {
+            paths: _,
+            total_size,
+            file_count: _,
+            selection_type: _,
+        } = v;
+        typed(total_size);
+    }
+
+    fn manifest(v: &Manifest) {
+        let Manifest {
+            version: _,
+            created_at: _,
+            files: _,
+            total_size,
+            file_count: _,
+        } = v;
+        typed(total_size);
+    }
+
+    fn upload_metadata(v: &UploadMetadata, part: &UploadPartChecksum) {
+        let UploadMetadata {
+            file_name: _,
+            file_size,
+            sha256_hex: _,
+            sha256_base64: _,
+            md5_hex: _,
+            md5_base64: _,
+            part_size_bytes,
+            parts: _,
+            multipart_etag: _,
+            from_cache: _,
+        } = v;
+        typed(file_size);
+        typed(part_size_bytes);
+
+        let UploadPartChecksum {
+            part_number: _,
+            offset,
+            size,
+            md5_hex: _,
+            md5_base64: _,
+        } = part;
+        typed(offset);
+        typed(size);
+    }
+
+    fn verify_manifest(v: &VerifyManifestResponse) {
+        let VerifyManifestResponse {
+            is_valid: _,
+            message: _,
+            file_count: _,
+            total_size,
+            signature: _,
+            strictness: _,
+            unexpected,
+        } = v;
+        typed(total_size);
+        if let Some(unexpected) = unexpected {
+            typed(&unexpected.total_size);
+            for entry in unexpected.files.iter().chain(&unexpected.directories) {
+                typed(&entry.size);
+            }
+        }
+    }
+
+    fn export_key(v: &ExportKeyResponse) {
+        let ExportKeyResponse {
+            success: _,
+            exported_file: _,
+            file_size,
+        } = v;
+        typed(file_size);
+    }
+
+    fn progress(update: &ProgressUpdate, response: &GetProgressResponse) {
+        let ProgressUpdate {
+            operation_id: _,
+            progress: _,
+            message: _,
+            details: _,
+            timestamp: _,
+            estimated_time_remaining,
+            io_priority: _,
+            trace_id: _,
+        } = update;
+        typed(estimated_time_remaining);
+
+        let GetProgressResponse {
+            operation_id: _,
+            progress: _,
+            message: _,
+            details: _,
+            timestamp: _,
+            estimated_time_remaining,
+            is_complete: _,
+            format_hints: _,
+            io_priority: _,
+            trace_id: _,
+        } = response;
+        typed(estimated_time_remaining);
+    }
+
+    fn progress_details(v: &ProgressDetails) {
+        match v {
+            ProgressDetails::FileOperation {
+                current_file: _,
+                total_files: _,
+                current_file_progress: _,
+                current_file_size,
+                total_size,
+            } => {
+                typed(current_file_size);
+                typed(total_size);
+            }
+            ProgressDetails::Encryption {
+                bytes_processed,
+                total_bytes,
+                encryption_rate: _,
+            }
+            | ProgressDetails::Decryption {
+                bytes_processed,
+                total_bytes,
+                decryption_rate: _,
+            }
+            | ProgressDetails::ArchiveOperation {
+                files_processed: _,
+                total_files: _,
+                bytes_processed,
+                total_bytes,
+                compression_ratio: _,
+            } => {
+                typed(bytes_processed);
+                typed(total_bytes);
+            }
+            ProgressDetails::ManifestOperation {
+                files_verified: _,
+                total_files: _,
+                current_file: _,
+            }
+            | ProgressDetails::YubiKeyOperation {
+                operation: _,
+                phase: _,
+                requires_interaction: _,
+                context: _,
+            }
+            | ProgressDetails::Maintenance {
+                vault_id: _,
+                task: _,
+                cells_completed: _,
+                total_cells: _,
+            }
+            | ProgressDetails::BatchDecryption {
+                archive_id: _,
+                archives_completed: _,
+                total_archives: _,
+                waiting_for_touch: _,
+            } => {}
+        }
+    }
+
+    fn encryption_status(v: &EncryptionStatusResponse) {
+        let EncryptionStatusResponse {
+            operation_id: _,
+            status: _,
+            progress_percentage: _,
+            current_file: _,
+            total_files: _,
+            processed_files: _,
+            total_size,
+            processed_size,
+            estimated_time_remaining,
+            error_message: _,
+            format_hints: _,
+        } = v;
+        typed(total_size);
+        typed(processed_size);
+        typed(estimated_time_remaining);
+    }
+
+    fn vault_statistics(v: &VaultStatistics, global: &GlobalVaultStatistics) {
+        let VaultStatistics {
+            vault_id: _,
+            vault_name: _,
+            description: _,
+            status: _,
+            encryption_count: _,
+            created_at: _,
+            last_encrypted_at: _,
+            last_encrypted_by: _,
+            file_count: _,
+            total_size_bytes,
+            key_statistics: _,
+            archive_exists: _,
+            manifest_exists: _,
+            item_statistics: _,
+            retention,
+            trend,
+            format_hints: _,
+        } = v;
+        typed(total_size_bytes);
+        typed(&retention.reclaimable);
+        typed(&trend.growth_30d);
+        typed(&trend.size_30d_ago);
+        typed(&trend.average_archive_size);
+
+        let GlobalVaultStatistics {
+            total_vaults: _,
+            active_vaults: _,
+            new_vaults: _,
+            orphaned_vaults: _,
+            total_encryptions: _,
+            total_files: _,
+            total_size_bytes,
+            vault_statistics: _,
+            format_hints: _,
+        } = global;
+        typed(total_size_bytes);
+    }
+
+    fn statistics_record(v: &StatisticsRecord) {
+        let StatisticsRecord {
+            recorded_at: _,
+            archive_count: _,
+            archive_files: _,
+            total_encrypted,
+            total_file_count: _,
+            largest_archive,
+        } = v;
+        typed(total_encrypted);
+        typed(largest_archive);
+    }
+
+    fn encrypted_archive(v: &EncryptedArchive, content: &ArchiveContent) {
+        let EncryptedArchive {
+            filename: _,
+            encrypted_at: _,
+            total_files: _,
+            total_size,
+            contents: _,
+            format_hints: _,
+        } = v;
+        typed(total_size);
+
+        let ArchiveContent {
+            file: _,
+            size,
+            hash: _,
+            format_hints: _,
+        } = content;
+        typed(size);
+    }
+
+    fn maintenance_cell(v: &MaintenanceCell) {
+        let MaintenanceCell {
+            vault_id: _,
+            vault_name: _,
+            task: _,
+            status: _,
+            duration_ms,
+            issues: _,
+            actions: _,
+            error: _,
+        } = v;
+        typed(duration_ms);
+    }
+
+    fn batch_archive_result(v: &BatchArchiveResult) {
+        let BatchArchiveResult {
+            archive_id: _,
+            archive_name: _,
+            status: _,
+            output_dir: _,
+            extracted_file_count: _,
+            touch_prompted: _,
+            duration_ms,
+            error: _,
+        } = v;
+        typed(duration_ms);
+    }
+
+    fn browse_session(v: &BrowseSessionInfo) {
+        let BrowseSessionInfo {
+            session_id: _,
+            vault_id: _,
+            archive_id: _,
+            url: _,
+            file_count: _,
+            memory_bytes,
+            spilled_bytes,
+            idle_timeout_ms,
+        } = v;
+        typed(memory_bytes);
+        typed(spilled_bytes);
+        typed(idle_timeout_ms);
+    }
+
+    fn benchmark(v: &BenchmarkResult, sizes: &BenchmarkSizes, phase: &PhaseThroughput) {
+        let BenchmarkResult {
+            benchmark_id: _,
+            profile: _,
+            sizes: _,
+            started_at: _,
+            file_count: _,
+            input_size,
+            archive_size,
+            phases: _,
+            encrypt_duration_ms,
+            decrypt_duration_ms,
+            total_duration_ms,
+            peak_memory,
+        } = v;
+        typed(input_size);
+        typed(archive_size);
+        typed(encrypt_duration_ms);
+        typed(decrypt_duration_ms);
+        typed(total_duration_ms);
+        typed(peak_memory);
+
+        let BenchmarkSizes {
+            small_file_count: _,
+            small_file_size,
+            large_file_size,
+        } = sizes;
+        typed(small_file_size);
+        typed(large_file_size);
+
+        let PhaseThroughput {
+            phase: _,
+            bytes,
+            duration_ms,
+            megabytes_per_second: _,
+        } = phase;
+        typed(bytes);
+        typed(duration_ms);
+    }
+
+    fn timing_breakdown(v: &TimingBreakdown, phase: &PhaseTiming) {
+        let TimingBreakdown {
+            phases: _,
+            total_ms,
+            unaccounted_ms,
+            hash_reads: _,
+        } = v;
+        typed(total_ms);
+        typed(unaccounted_ms);
+
+        let PhaseTiming {
+            phase: _,
+            offset_ms,
+            span_ms,
+            exclusive_ms,
+            bytes,
+            megabytes_per_second: _,
+        } = phase;
+        typed(offset_ms);
+        typed(span_ms);
+        typed(exclusive_ms);
+        typed(bytes);
+    }
+
+    fn pty_session(v: &PtySessionInfo) {
+        let PtySessionInfo {
+            id: _,
+            command: _,
+            started_at: _,
+            last_output_at: _,
+            idle_ms,
+            waiting_for_touch: _,
+            terminated: _,
+        } = v;
+        typed(idle_ms);
+    }
+
+    fn resilient_source_config(v: &ResilientSourceConfig) {
+        let ResilientSourceConfig {
+            read_retries: _,
+            retry_backoff,
+            parallelism: _,
+            slow_media_parallelism: _,
+            slow_media_threshold,
+            throughput_sample_files: _,
+            verify_sample_size: _,
+        } = v;
+        typed(retry_backoff);
+        typed(slow_media_threshold);
+    }
+
+    fn resilient_source_report(v: &ResilientSourceReport) {
+        let ResilientSourceReport {
+            retries: _,
+            retried_files: _,
+            sampled_throughput,
+            slow_media: _,
+            parallelism: _,
+            verified_files: _,
+        } = v;
+        typed(sampled_throughput);
+    }
+}
@@ -4,8 +4,75 @@
 
 use super::{CommandError, ErrorCode};
 use crate::constants::*;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
+/// Rule an input field is checked against (see `commands::validation`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+#[serde(rename_all = "snake_case")]
+pub enum ValidationRule {
+    NonEmpty,
+    NonEmptyId,
+    NonEmptyList,
+    ExistingVaultId,
+    ExistingFile,
+    ExistingDirectory,
+    PathWithinAllowedRoots,
+    LabelFormat,
+    PassphraseStrength,
+    PositiveByteSize,
+    InRange,
+    MaxLength,
+    /// A command-specific format, such as a content type pattern
+    Format,
+}
+
+impl ValidationRule {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::NonEmpty => "non_empty",
+            Self::NonEmptyId => "non_empty_id",
+            Self::NonEmptyList => "non_empty_list",
+            Self::ExistingVaultId => "existing_vault_id",
+            Self::ExistingFile => "existing_file",
+            Self::ExistingDirectory => "existing_directory",
+            Self::PathWithinAllowedRoots => "path_within_allowed_roots",
+            Self::LabelFormat => "label_format",
+            Self::PassphraseStrength => "passphrase_strength",
+            Self::PositiveByteSize => "positive_byte_size",
+            Self::InRange => "in_range",
+            Self::MaxLength => "max_length",
+            Self::Format => "format",
+        }
+    }
+
+    /// Localization key for the rule's message
+    pub fn message_key(&self) -> String {
+        format!("validation.{}", self.as_str())
+    }
+}
+
+/// The input field a command rejected and the rule it broke
+///
+/// `field` is the field's name in the command input, so the frontend can
+/// mark the matching form field.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+pub struct ValidationFailure {
+    pub field: String,
+    pub rule: ValidationRule,
+    pub message_key: String,
+}
+
+impl ValidationFailure {
+    pub fn new(field: impl Into<String>, rule: ValidationRule) -> Self {
+        Self {
+            field: field.into(),
+            rule,
+            message_key: rule.message_key(),
+        }
+    }
+}
+
 /// Trait for validatable command inputs
 pub trait ValidateInput {
     fn validate(&self) -> Result<(), Box<CommandError>>;
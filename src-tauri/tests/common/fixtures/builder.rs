@@ -0,0 +1,663 @@
+//! Deterministic fixture trees
+//!
+//! `FixtureBuilder` turns a `u64` seed into a directory tree: file count,
+//! size distribution, naming, duplicates, symlinks, unicode and
+//! case-colliding names, deep paths, zero-byte and sparse files. The same
+//! seed always yields the same paths and bytes on every machine, so a
+//! failing test can be reproduced from its seed alone.
+//!
+//! Randomness comes from a built-in SplitMix64 generator rather than `rand`,
+//! whose algorithms may change between releases.
+//!
+//! This file is shared with the `generate-fixtures` binary via `#[path]`, so
+//! it depends only on std and `sha2`.
+
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+
+const WRITE_CHUNK: usize = 64 * 1024;
+
+const NAME_WORDS: [&str; 12] = [
+    "wallet",
+    "descriptor",
+    "backup",
+    "notes",
+    "keys",
+    "photo",
+    "invoice",
+    "contract",
+    "statement",
+    "config",
+    "ledger",
+    "letter",
+];
+
+const NAME_EXTENSIONS: [&str; 6] = ["txt", "dat", "json", "pdf", "jpg", "csv"];
+
+const UNICODE_STEMS: [&str; 6] = [
+    "résumé",
+    "日本語のメモ",
+    "файл",
+    "Ünïcödé",
+    "مستند",
+    "clé_🔑",
+];
+
+/// Small, portable PRNG (SplitMix64)
+#[derive(Debug, Clone)]
+pub struct FixtureRng(u64);
+
+impl FixtureRng {
+    pub fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform value in `0..n` (0 when `n` is 0)
+    pub fn below(&mut self, n: u64) -> u64 {
+        if n == 0 { 0 } else { self.next_u64() % n }
+    }
+
+    /// Uniform value in `min..=max`
+    pub fn between(&mut self, min: u64, max: u64) -> u64 {
+        let (low, high) = (min.min(max), min.max(max));
+        low + self.below(high - low + 1)
+    }
+
+    pub fn fill(&mut self, buf: &mut [u8]) {
+        for chunk in buf.chunks_mut(8) {
+            let bytes = self.next_u64().to_le_bytes();
+            chunk.copy_from_slice(&bytes[..chunk.len()]);
+        }
+    }
+}
+
+/// How file sizes are drawn
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SizeDistribution {
+    Fixed(u64),
+    /// Every size in the range equally likely
+    Uniform {
+        min: u64,
+        max: u64,
+    },
+    /// Every power of two in the range equally likely (many small, few large)
+    LogUniform {
+        min: u64,
+        max: u64,
+    },
+}
+
+impl SizeDistribution {
+    fn sample(&self, rng: &mut FixtureRng) -> u64 {
+        match *self {
+            Self::Fixed(size) => size,
+            Self::Uniform { min, max } => rng.between(min, max),
+            Self::LogUniform { min, max } => {
+                let low = min.max(1);
+                let high = max.max(low);
+                let exponent = rng.between(u64::from(low.ilog2()), u64::from(high.ilog2()));
+                let bucket_low = (1u64 << exponent).max(low);
+                let bucket_high = ((1u64 << exponent) * 2 - 1).min(high);
+                rng.between(bucket_low, bucket_high)
+            }
+        }
+    }
+}
+
+/// How regular file names are generated
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NamePattern {
+    /// `{prefix}_0001.dat`
+    Numbered { prefix: String },
+    /// Realistic names such as `wallet-descriptor-3.txt`
+    Words,
+}
+
+/// What a fixture entry holds
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FixtureContent {
+    /// `len` bytes generated from `seed`
+    Bytes { seed: u64, len: u64 },
+    /// `len` zero bytes, written as a hole where the filesystem supports it
+    Sparse { len: u64 },
+    /// Relative symlink to another entry (created on Unix only)
+    Symlink { target: PathBuf },
+}
+
+/// One path in a fixture tree
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FixtureEntry {
+    /// Path relative to the tree root
+    pub path: PathBuf,
+    pub content: FixtureContent,
+}
+
+impl FixtureEntry {
+    /// Logical size in bytes (0 for symlinks)
+    pub fn len(&self) -> u64 {
+        match self.content {
+            FixtureContent::Bytes { len, .. } | FixtureContent::Sparse { len } => len,
+            FixtureContent::Symlink { .. } => 0,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn is_file(&self) -> bool {
+        !matches!(self.content, FixtureContent::Symlink { .. })
+    }
+
+    /// The entry's bytes (not for sparse files, which may be huge)
+    pub fn bytes(&self) -> Vec<u8> {
+        match self.content {
+            FixtureContent::Bytes { seed, len } => {
+                let mut data = vec![0; len as usize];
+                FixtureRng::new(seed).fill(&mut data);
+                data
+            }
+            FixtureContent::Sparse { len } => vec![0; len as usize],
+            FixtureContent::Symlink { .. } => Vec::new(),
+        }
+    }
+}
+
+/// A materialized fixture tree
+#[derive(Debug, Clone)]
+pub struct FixtureTree {
+    pub root: PathBuf,
+    pub seed: u64,
+    /// Entries actually created, in creation order
+    pub entries: Vec<FixtureEntry>,
+}
+
+impl FixtureTree {
+    /// Absolute paths of regular files (duplicates and sparse files included)
+    pub fn files(&self) -> Vec<PathBuf> {
+        self.entries
+            .iter()
+            .filter(|e| e.is_file())
+            .map(|e| self.root.join(&e.path))
+            .collect()
+    }
+
+    /// Relative paths of regular files
+    pub fn relative_files(&self) -> Vec<PathBuf> {
+        self.entries
+            .iter()
+            .filter(|e| e.is_file())
+            .map(|e| e.path.clone())
+            .collect()
+    }
+
+    /// Sum of regular file sizes
+    pub fn total_size(&self) -> u64 {
+        self.entries.iter().map(FixtureEntry::len).sum()
+    }
+
+    pub fn entry(&self, relative: impl AsRef<Path>) -> Option<&FixtureEntry> {
+        self.entries.iter().find(|e| e.path == relative.as_ref())
+    }
+
+    /// SHA-256 over every entry as found on disk
+    ///
+    /// Sparse files contribute only their length so hashing stays cheap.
+    pub fn digest(&self) -> io::Result<String> {
+        let mut entries: Vec<&FixtureEntry> = self.entries.iter().collect();
+        entries.sort_by(|a, b| a.path.cmp(&b.path));
+
+        let mut hasher = Sha256::new();
+        for entry in entries {
+            let path = self.root.join(&entry.path);
+            hasher.update(portable_path(&entry.path).as_bytes());
+            match &entry.content {
+                FixtureContent::Bytes { .. } => {
+                    let mut file = fs::File::open(&path)?;
+                    let mut buf = vec![0; WRITE_CHUNK];
+                    loop {
+                        let read = file.read(&mut buf)?;
+                        if read == 0 {
+                            break;
+                        }
+                        hasher.update(&buf[..read]);
+                    }
+                }
+                FixtureContent::Sparse { .. } => {
+                    hasher.update(fs::metadata(&path)?.len().to_le_bytes());
+                }
+                FixtureContent::Symlink { .. } => {
+                    hasher.update(portable_path(&fs::read_link(&path)?).as_bytes());
+                }
+            }
+        }
+        Ok(hex_string(&hasher.finalize()))
+    }
+}
+
+/// Builds a deterministic directory tree from a seed
+#[derive(Debug, Clone)]
+pub struct FixtureBuilder {
+    seed: u64,
+    file_count: usize,
+    sizes: SizeDistribution,
+    names: NamePattern,
+    max_depth: usize,
+    duplicates: usize,
+    symlinks: usize,
+    unicode_names: usize,
+    case_collisions: usize,
+    zero_byte_files: usize,
+    deep_paths: usize,
+    deep_path_depth: usize,
+    sparse_files: Vec<(String, u64)>,
+}
+
+impl FixtureBuilder {
+    /// Four small numbered files in a flat directory
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            file_count: 4,
+            sizes: SizeDistribution::Uniform { min: 16, max: 1024 },
+            names: NamePattern::Numbered {
+                prefix: "file".to_string(),
+            },
+            max_depth: 0,
+            duplicates: 0,
+            symlinks: 0,
+            unicode_names: 0,
+            case_collisions: 0,
+            zero_byte_files: 0,
+            deep_paths: 0,
+            deep_path_depth: 0,
+            sparse_files: Vec::new(),
+        }
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Number of regular (non-special) files
+    pub fn file_count(mut self, count: usize) -> Self {
+        self.file_count = count;
+        self
+    }
+
+    pub fn sizes(mut self, sizes: SizeDistribution) -> Self {
+        self.sizes = sizes;
+        self
+    }
+
+    pub fn names(mut self, names: NamePattern) -> Self {
+        self.names = names;
+        self
+    }
+
+    /// Regular files are placed up to this many directories deep
+    pub fn max_depth(mut self, depth: usize) -> Self {
+        self.max_depth = depth;
+        self
+    }
+
+    /// Extra files that repeat the bytes of a regular file
+    pub fn duplicates(mut self, count: usize) -> Self {
+        self.duplicates = count;
+        self
+    }
+
+    /// Relative symlinks to regular files (Unix only)
+    pub fn symlinks(mut self, count: usize) -> Self {
+        self.symlinks = count;
+        self
+    }
+
+    /// Give this many regular files non-ASCII names
+    pub fn unicode_names(mut self, count: usize) -> Self {
+        self.unicode_names = count;
+        self
+    }
+
+    /// Pairs of files whose names differ only in case
+    pub fn case_collisions(mut self, pairs: usize) -> Self {
+        self.case_collisions = pairs;
+        self
+    }
+
+    pub fn zero_byte_files(mut self, count: usize) -> Self {
+        self.zero_byte_files = count;
+        self
+    }
+
+    /// Files nested `depth` directories deep
+    pub fn deep_paths(mut self, count: usize, depth: usize) -> Self {
+        self.deep_paths = count;
+        self.deep_path_depth = depth;
+        self
+    }
+
+    /// A sparse file of `len` bytes at `relative_path`
+    pub fn sparse_file(mut self, relative_path: &str, len: u64) -> Self {
+        self.sparse_files.push((relative_path.to_string(), len));
+        self
+    }
+
+    /// Entries this builder produces, without touching the filesystem
+    pub fn plan(&self) -> Vec<FixtureEntry> {
+        let mut rng = FixtureRng::new(self.seed);
+        let mut entries = Vec::new();
+
+        for index in 0..self.file_count {
+            let depth = rng.below(self.max_depth as u64 + 1);
+            let mut path = PathBuf::new();
+            for _ in 0..depth {
+                path.push(format!("dir_{}", rng.below(3)));
+            }
+            path.push(self.file_name(index, &mut rng));
+
+            let len = self.sizes.sample(&mut rng);
+            entries.push(FixtureEntry {
+                path,
+                content: FixtureContent::Bytes {
+                    seed: rng.next_u64(),
+                    len,
+                },
+            });
+        }
+        let regular = entries.len();
+
+        for index in 0..self.zero_byte_files {
+            entries.push(FixtureEntry {
+                path: PathBuf::from("empty").join(format!("empty_{index}.dat")),
+                content: FixtureContent::Bytes { seed: 0, len: 0 },
+            });
+        }
+
+        for index in 0..self.case_collisions {
+            for name in [format!("Case_{index}.txt"), format!("case_{index}.txt")] {
+                entries.push(FixtureEntry {
+                    path: PathBuf::from("case").join(name),
+                    content: FixtureContent::Bytes {
+                        seed: rng.next_u64(),
+                        len: rng.between(8, 256),
+                    },
+                });
+            }
+        }
+
+        for index in 0..self.deep_paths {
+            let mut path = PathBuf::from("deep");
+            for level in 0..self.deep_path_depth {
+                path.push(format!("d{level:02}"));
+            }
+            path.push(format!("deep_{index}.txt"));
+            entries.push(FixtureEntry {
+                path,
+                content: FixtureContent::Bytes {
+                    seed: rng.next_u64(),
+                    len: rng.between(8, 256),
+                },
+            });
+        }
+
+        if regular > 0 {
+            for index in 0..self.duplicates {
+                let source = entries[rng.below(regular as u64) as usize].clone();
+                let name = source
+                    .path
+                    .file_name()
+                    .unwrap_or_default()
+                    .to_string_lossy();
+                entries.push(FixtureEntry {
+                    path: PathBuf::from("dups").join(format!("dup_{index}_{name}")),
+                    content: source.content,
+                });
+            }
+
+            for index in 0..self.symlinks {
+                let source = &entries[rng.below(regular as u64) as usize];
+                entries.push(FixtureEntry {
+                    path: PathBuf::from("links").join(format!("link_{index}")),
+                    content: FixtureContent::Symlink {
+                        target: Path::new("..").join(&source.path),
+                    },
+                });
+            }
+        }
+
+        for (path, len) in &self.sparse_files {
+            entries.push(FixtureEntry {
+                path: PathBuf::from(path),
+                content: FixtureContent::Sparse { len: *len },
+            });
+        }
+
+        entries
+    }
+
+    /// Create the tree under `root`
+    ///
+    /// Files are created with `create_new`, so on a case-insensitive
+    /// filesystem a case-collision pair fails with `AlreadyExists` rather
+    /// than silently overwriting. Symlinks are skipped on non-Unix platforms.
+    pub fn build(&self, root: &Path) -> io::Result<FixtureTree> {
+        fs::create_dir_all(root)?;
+        let mut created = Vec::new();
+
+        for entry in self.plan() {
+            let path = root.join(&entry.path);
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+
+            match &entry.content {
+                FixtureContent::Bytes { seed, len } => write_bytes(&path, *seed, *len)?,
+                FixtureContent::Sparse { len } => {
+                    fs::File::create_new(&path)?.set_len(*len)?;
+                }
+                FixtureContent::Symlink { target } => {
+                    #[cfg(unix)]
+                    std::os::unix::fs::symlink(target, &path)?;
+                    #[cfg(not(unix))]
+                    {
+                        let _ = target;
+                        continue;
+                    }
+                }
+            }
+            created.push(entry);
+        }
+
+        Ok(FixtureTree {
+            root: root.to_path_buf(),
+            seed: self.seed,
+            entries: created,
+        })
+    }
+
+    fn file_name(&self, index: usize, rng: &mut FixtureRng) -> String {
+        let extension = NAME_EXTENSIONS[rng.below(NAME_EXTENSIONS.len() as u64) as usize];
+        if index < self.unicode_names {
+            let stem = UNICODE_STEMS[index % UNICODE_STEMS.len()];
+            return format!("{stem}_{index}.{extension}");
+        }
+
+        match &self.names {
+            NamePattern::Numbered { prefix } => format!("{prefix}_{index:04}.dat"),
+            NamePattern::Words => {
+                let first = NAME_WORDS[rng.below(NAME_WORDS.len() as u64) as usize];
+                let second = NAME_WORDS[rng.below(NAME_WORDS.len() as u64) as usize];
+                format!("{first}-{second}-{index}.{extension}")
+            }
+        }
+    }
+}
+
+/// True when `dir` distinguishes file names that differ only in case
+pub fn is_case_sensitive(dir: &Path) -> io::Result<bool> {
+    let probe = dir.join(".CaseProbe");
+    fs::write(&probe, b"")?;
+    let sensitive = !dir.join(".caseprobe").exists();
+    fs::remove_file(&probe)?;
+    Ok(sensitive)
+}
+
+fn write_bytes(path: &Path, seed: u64, len: u64) -> io::Result<()> {
+    let mut file = fs::File::create_new(path)?;
+    let mut rng = FixtureRng::new(seed);
+    let mut buf = vec![0; WRITE_CHUNK];
+    let mut remaining = len;
+    while remaining > 0 {
+        let chunk = remaining.min(WRITE_CHUNK as u64) as usize;
+        rng.fill(&mut buf[..chunk]);
+        file.write_all(&buf[..chunk])?;
+        remaining -= chunk as u64;
+    }
+    Ok(())
+}
+
+/// Forward-slash path so digests match across platforms
+fn portable_path(path: &Path) -> String {
+    path.components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+fn hex_string(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn rich_builder(seed: u64) -> FixtureBuilder {
+        FixtureBuilder::new(seed)
+            .file_count(12)
+            .names(NamePattern::Words)
+            .sizes(SizeDistribution::LogUniform {
+                min: 1,
+                max: 200_000,
+            })
+            .max_depth(3)
+            .duplicates(2)
+            .symlinks(2)
+            .unicode_names(3)
+            .zero_byte_files(2)
+            .deep_paths(1, 8)
+    }
+
+    fn read_tree(tree: &FixtureTree) -> Vec<(PathBuf, Vec<u8>)> {
+        tree.relative_files()
+            .into_iter()
+            .map(|rel| {
+                let bytes = fs::read(tree.root.join(&rel)).unwrap();
+                (rel, bytes)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_rng_is_stable() {
+        // Pinned so a generator change can't silently alter every fixture
+        let mut rng = FixtureRng::new(0);
+        assert_eq!(rng.next_u64(), 0xE220_A839_7B1D_CDAF);
+        assert_eq!(rng.next_u64(), 0x6E78_9E6A_A1B9_65F4);
+    }
+
+    #[test]
+    fn test_same_seed_builds_identical_trees() {
+        let first = TempDir::new().unwrap();
+        let second = TempDir::new().unwrap();
+
+        let a = rich_builder(42).build(first.path()).unwrap();
+        let b = rich_builder(42).build(second.path()).unwrap();
+
+        assert_eq!(a.entries, b.entries);
+        assert_eq!(read_tree(&a), read_tree(&b));
+        assert_eq!(a.digest().unwrap(), b.digest().unwrap());
+    }
+
+    #[test]
+    fn test_different_seeds_differ() {
+        assert_ne!(rich_builder(1).plan(), rich_builder(2).plan());
+    }
+
+    #[test]
+    fn test_plan_honours_counts_and_sizes() {
+        let plan = FixtureBuilder::new(7)
+            .file_count(20)
+            .sizes(SizeDistribution::Uniform { min: 10, max: 20 })
+            .zero_byte_files(3)
+            .case_collisions(2)
+            .deep_paths(2, 30)
+            .duplicates(4)
+            .plan();
+
+        assert_eq!(plan.len(), 20 + 3 + 4 + 2 + 4);
+        assert!(plan[..20].iter().all(|e| (10..=20).contains(&e.len())));
+        assert_eq!(plan.iter().filter(|e| e.is_empty()).count(), 3);
+        assert!(plan.iter().any(|e| e.path.components().count() == 32));
+
+        // Duplicates repeat an existing file's bytes
+        for duplicate in plan.iter().filter(|e| e.path.starts_with("dups")) {
+            assert!(plan[..20].iter().any(|e| e.content == duplicate.content));
+        }
+    }
+
+    #[test]
+    fn test_log_uniform_stays_in_range() {
+        let mut rng = FixtureRng::new(3);
+        let sizes = SizeDistribution::LogUniform {
+            min: 100,
+            max: 5000,
+        };
+        for _ in 0..1000 {
+            assert!((100..=5000).contains(&sizes.sample(&mut rng)));
+        }
+    }
+
+    #[test]
+    fn test_build_writes_planned_bytes() {
+        let temp = TempDir::new().unwrap();
+        let tree = rich_builder(9).build(temp.path()).unwrap();
+
+        for entry in tree.entries.iter().filter(|e| e.is_file()) {
+            let on_disk = fs::read(tree.root.join(&entry.path)).unwrap();
+            assert_eq!(on_disk, entry.bytes(), "{}", entry.path.display());
+        }
+
+        #[cfg(unix)]
+        for link in tree.entries.iter().filter(|e| !e.is_file()) {
+            assert!(tree.root.join(&link.path).exists(), "dangling symlink");
+        }
+    }
+
+    #[test]
+    fn test_case_collisions_never_overwrite() {
+        let temp = TempDir::new().unwrap();
+        let result = FixtureBuilder::new(5)
+            .file_count(0)
+            .case_collisions(1)
+            .build(temp.path());
+
+        if is_case_sensitive(temp.path()).unwrap() {
+            assert_eq!(result.unwrap().files().len(), 2);
+        } else {
+            assert_eq!(result.unwrap_err().kind(), io::ErrorKind::AlreadyExists);
+        }
+    }
+}
@@ -4,8 +4,17 @@
 //! - Isolated: Each test gets its own data
 //! - Consistent: Same structure across test runs
 //! - Parallel-safe: No shared state between tests
+//!
+//! File trees come from the seeded [`FixtureBuilder`] and the named sets in
+//! [`sets`], which the `generate-fixtures` binary can also write to disk.
+
+pub mod builder;
+pub mod sets;
+
+pub use builder::*;
+pub use sets::*;
 
-use super::TestSuiteConfig;
+use crate::common::TestSuiteConfig;
 use barqly_vault_lib::services::crypto::infrastructure::KeyPair;
 use barqly_vault_lib::services::key_management::passphrase::generate_keypair;
 use barqly_vault_lib::services::key_management::shared::{KeyInfo, save_encrypted_key};
@@ -139,8 +148,8 @@ impl StorageFixtures {
     /// Returns tuple of (key_infos, cleanup) where cleanup should be kept alive for the test duration
     pub fn create_test_key_store(
         _config: &TestSuiteConfig,
-    ) -> (Vec<KeyInfo>, super::cleanup::TestCleanup) {
-        use super::cleanup::TestCleanup;
+    ) -> (Vec<KeyInfo>, crate::common::cleanup::TestCleanup) {
+        use crate::common::cleanup::TestCleanup;
 
         // Create test keys and save them
         let test_keys = CryptoFixtures::create_test_key_pairs(3);
@@ -0,0 +1,176 @@
+//! Named fixture sets
+//!
+//! Each set is a seeded [`FixtureBuilder`] recipe, so tests and the
+//! `generate-fixtures` binary produce byte-identical trees from a name.
+
+use super::builder::{FixtureBuilder, NamePattern, SizeDistribution};
+
+/// Size of the `edge-sparse-4gb` file: just past the 32-bit boundary
+pub const SPARSE_4GB_LEN: u64 = (4 << 30) + 1;
+
+/// A fixture recipe that can be looked up by name
+#[derive(Debug, Clone, Copy)]
+pub struct NamedFixtureSet {
+    pub name: &'static str,
+    pub description: &'static str,
+    /// Too large or slow for the default test run and generator invocation
+    pub heavy: bool,
+    pub build: fn() -> FixtureBuilder,
+}
+
+pub const FIXTURE_SETS: &[NamedFixtureSet] = &[
+    NamedFixtureSet {
+        name: "encryption-basic",
+        description: "Nested mix of small and medium files to encrypt",
+        heavy: false,
+        build: || {
+            FixtureBuilder::new(0x0E9C_0001)
+                .file_count(8)
+                .names(NamePattern::Words)
+                .sizes(SizeDistribution::LogUniform {
+                    min: 32,
+                    max: 64 * 1024,
+                })
+                .max_depth(2)
+        },
+    },
+    NamedFixtureSet {
+        name: "decryption-basic",
+        description: "Small, medium and large flat files for decrypt round trips",
+        heavy: false,
+        build: || {
+            FixtureBuilder::new(0x0DEC_0001)
+                .file_count(3)
+                .names(NamePattern::Numbered {
+                    prefix: "test".to_string(),
+                })
+                .sizes(SizeDistribution::LogUniform {
+                    min: 16,
+                    max: 8 * 1024,
+                })
+        },
+    },
+    NamedFixtureSet {
+        name: "file-ops-tree",
+        description: "Wallet-style folder tree with a duplicate file",
+        heavy: false,
+        build: || {
+            FixtureBuilder::new(0xF11E_0001)
+                .file_count(10)
+                .names(NamePattern::Words)
+                .sizes(SizeDistribution::LogUniform {
+                    min: 16,
+                    max: 16 * 1024,
+                })
+                .max_depth(3)
+                .duplicates(1)
+        },
+    },
+    NamedFixtureSet {
+        name: "manifest-mixed",
+        description: "Many files of varied size, duplicates and symlinks",
+        heavy: false,
+        build: || {
+            FixtureBuilder::new(0x3A41_0001)
+                .file_count(100)
+                .names(NamePattern::Words)
+                .sizes(SizeDistribution::LogUniform {
+                    min: 1,
+                    max: 256 * 1024,
+                })
+                .max_depth(4)
+                .duplicates(5)
+                .symlinks(3)
+        },
+    },
+    NamedFixtureSet {
+        name: "edge-case-collision",
+        description: "File names that differ only in case",
+        heavy: false,
+        build: || {
+            FixtureBuilder::new(0xED6E_0001)
+                .file_count(1)
+                .case_collisions(3)
+        },
+    },
+    NamedFixtureSet {
+        name: "edge-deep-paths",
+        description: "Files nested 40 directories deep",
+        heavy: false,
+        build: || {
+            FixtureBuilder::new(0xED6E_0002)
+                .file_count(1)
+                .deep_paths(2, 40)
+        },
+    },
+    NamedFixtureSet {
+        name: "edge-zero-byte",
+        description: "Empty files alongside regular ones",
+        heavy: false,
+        build: || {
+            FixtureBuilder::new(0xED6E_0003)
+                .file_count(2)
+                .zero_byte_files(4)
+        },
+    },
+    NamedFixtureSet {
+        name: "edge-unicode",
+        description: "Non-ASCII file names across several scripts",
+        heavy: false,
+        build: || {
+            FixtureBuilder::new(0xED6E_0004)
+                .file_count(6)
+                .unicode_names(6)
+                .max_depth(1)
+        },
+    },
+    NamedFixtureSet {
+        name: "edge-sparse-4gb",
+        description: "A sparse file just over 4 GiB",
+        heavy: true,
+        build: || {
+            FixtureBuilder::new(0xED6E_0005)
+                .file_count(1)
+                .sparse_file("sparse/large.bin", SPARSE_4GB_LEN)
+        },
+    },
+];
+
+/// Look up a named set
+pub fn fixture_set(name: &str) -> Option<&'static NamedFixtureSet> {
+    FIXTURE_SETS.iter().find(|set| set.name == name)
+}
+
+/// Builder for a named set
+///
+/// # Panics
+/// If no set has this name.
+pub fn fixture(name: &str) -> FixtureBuilder {
+    let set = fixture_set(name).unwrap_or_else(|| panic!("Unknown fixture set '{name}'"));
+    (set.build)()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_set_names_are_unique() {
+        let names: HashSet<_> = FIXTURE_SETS.iter().map(|set| set.name).collect();
+        assert_eq!(names.len(), FIXTURE_SETS.len());
+    }
+
+    #[test]
+    fn test_sets_are_deterministic() {
+        for set in FIXTURE_SETS {
+            assert_eq!((set.build)().plan(), (set.build)().plan(), "{}", set.name);
+        }
+    }
+
+    #[test]
+    fn test_sparse_set_crosses_4gb() {
+        let plan = fixture("edge-sparse-4gb").plan();
+        assert!(plan.iter().any(|entry| entry.len() > u64::from(u32::MAX)));
+    }
+}
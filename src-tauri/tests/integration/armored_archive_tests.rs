@@ -0,0 +1,123 @@
+//! Integration tests for ASCII-armored archives
+//!
+//! Tests cover an heir finding an armored archive on a drive:
+//! - Reading its preamble without a key
+//! - Decrypting it through the same path as binary archives
+//! - Stripping the preamble and handing the rest to the standard age CLI
+
+use barqly_vault_lib::services::crypto::infrastructure::age_armor::ARMOR_BEGIN_LINE;
+use barqly_vault_lib::services::crypto::infrastructure::{
+    ArchivePreamble, armor_archive, decrypt_data, encrypt_data, read_age_archive,
+    read_age_header_file, read_archive_preamble,
+};
+use barqly_vault_lib::services::key_management::passphrase::generate_keypair;
+use barqly_vault_lib::services::shared::infrastructure::resolve_bundled_binary;
+use std::process::Command;
+use tempfile::TempDir;
+
+const ARMOR_END_LINE: &str = "-----END AGE ENCRYPTED FILE-----";
+
+fn preamble(vault_name: Option<&str>) -> ArchivePreamble {
+    ArchivePreamble::new(chrono::Utc::now(), "barqly.vault.manifest/2", vault_name)
+}
+
+#[test]
+fn test_armored_archive_round_trip() {
+    let temp_dir = TempDir::new().unwrap();
+    let keypair = generate_keypair().unwrap();
+    let plaintext = b"passport scan and will".to_vec();
+
+    let binary = encrypt_data(&plaintext, &keypair.public_key).unwrap();
+    let armored = armor_archive(&binary, &preamble(None)).unwrap();
+    let archive = temp_dir.path().join("family-2024.age");
+    std::fs::write(&archive, &armored).unwrap();
+
+    // Readable without a key, and private by default
+    let found = read_archive_preamble(&archive).unwrap().unwrap();
+    assert_eq!(found.app, "Barqly Vault");
+    assert_eq!(found.vault_name, None);
+    assert_eq!(
+        found.recovery_url.as_deref(),
+        Some("https://barqly.com/recovery")
+    );
+    assert_eq!(
+        found.manifest_schema.as_deref(),
+        Some("barqly.vault.manifest/2")
+    );
+
+    // Header inspection and decryption see through the armor
+    assert_eq!(read_age_header_file(&archive).unwrap().x25519_count(), 1);
+    let decoded = read_age_archive(&archive).unwrap();
+    assert_eq!(decoded, binary);
+    assert_eq!(
+        decrypt_data(&decoded, &keypair.private_key).unwrap(),
+        plaintext
+    );
+
+    // Binary archives read unchanged and have no preamble
+    let binary_archive = temp_dir.path().join("family-2025.age");
+    std::fs::write(&binary_archive, &binary).unwrap();
+    assert_eq!(read_archive_preamble(&binary_archive).unwrap(), None);
+    assert_eq!(read_age_archive(&binary_archive).unwrap(), binary);
+}
+
+#[test]
+fn test_vault_name_only_when_opted_in() {
+    let keypair = generate_keypair().unwrap();
+    let binary = encrypt_data(b"data", &keypair.public_key).unwrap();
+
+    let private = armor_archive(&binary, &preamble(None)).unwrap();
+    assert!(!String::from_utf8_lossy(&private).contains("Family Docs"));
+
+    let named = armor_archive(&binary, &preamble(Some("Family Docs"))).unwrap();
+    assert!(String::from_utf8_lossy(&named).contains("# vault: Family Docs\n"));
+}
+
+/// The armor under the preamble is age's own format, so the age CLI reads it
+/// once the `#` lines are deleted, as the preamble instructs
+#[test]
+fn test_stripped_armor_is_standard_age_format() {
+    let temp_dir = TempDir::new().unwrap();
+    let keypair = generate_keypair().unwrap();
+    let plaintext = b"for my family".to_vec();
+    let binary = encrypt_data(&plaintext, &keypair.public_key).unwrap();
+    let armored = String::from_utf8(armor_archive(&binary, &preamble(None)).unwrap()).unwrap();
+
+    let stripped: String = armored
+        .lines()
+        .filter(|line| !line.starts_with('#'))
+        .map(|line| format!("{line}\n"))
+        .collect();
+    let lines: Vec<&str> = stripped.lines().collect();
+    assert_eq!(lines.first(), Some(&ARMOR_BEGIN_LINE));
+    assert_eq!(lines.last(), Some(&ARMOR_END_LINE));
+    assert!(lines[1..lines.len() - 1].iter().all(|line| {
+        line.len() <= 64
+            && line
+                .bytes()
+                .all(|b| b.is_ascii_alphanumeric() || b == b'+' || b == b'/' || b == b'=')
+    }));
+
+    // Only runs where the bundled age binary is installed
+    let Some(age) = resolve_bundled_binary("age") else {
+        return;
+    };
+    let stripped_path = temp_dir.path().join("family-2024.age");
+    let identity_path = temp_dir.path().join("identity.txt");
+    std::fs::write(&stripped_path, &stripped).unwrap();
+    std::fs::write(&identity_path, keypair.private_key.expose_secret()).unwrap();
+
+    let output = Command::new(age)
+        .arg("-d")
+        .arg("-i")
+        .arg(&identity_path)
+        .arg(&stripped_path)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "age CLI rejected the armor: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(output.stdout, plaintext);
+}
@@ -37,6 +37,12 @@ fn should_validate_decrypt_input_with_non_existent_directory() {
         passphrase: "TestPass123!@#".to_string(),
         output_dir: Some(non_existent_dir.to_string_lossy().to_string()),
         force_overwrite: None,
+        path_limit_strategy: None,
+        ownership_policy: None,
+        restore_filter: None,
+        io_priority: None,
+        session_ttl_minutes: None,
+        accept_last_attempt: None,
     };
 
     // When: Validating the input
@@ -79,6 +85,12 @@ fn should_validate_decrypt_input_with_existing_directory() {
         passphrase: "TestPass123!@#".to_string(),
         output_dir: Some(existing_dir.to_string_lossy().to_string()),
         force_overwrite: None,
+        path_limit_strategy: None,
+        ownership_policy: None,
+        restore_filter: None,
+        io_priority: None,
+        session_ttl_minutes: None,
+        accept_last_attempt: None,
     };
 
     // When: Validating the input
@@ -115,6 +127,12 @@ fn should_fail_validation_when_encrypted_file_missing() {
         passphrase: "TestPass123!@#".to_string(),
         output_dir: Some(test_base.to_string_lossy().to_string()),
         force_overwrite: None,
+        path_limit_strategy: None,
+        ownership_policy: None,
+        restore_filter: None,
+        io_priority: None,
+        session_ttl_minutes: None,
+        accept_last_attempt: None,
     };
 
     // When: Validating the input
@@ -152,6 +170,12 @@ fn should_fail_validation_when_encrypted_file_is_directory() {
         passphrase: "TestPass123!@#".to_string(),
         output_dir: Some(test_base.to_string_lossy().to_string()),
         force_overwrite: None,
+        path_limit_strategy: None,
+        ownership_policy: None,
+        restore_filter: None,
+        io_priority: None,
+        session_ttl_minutes: None,
+        accept_last_attempt: None,
     };
 
     // When: Validating the input
@@ -390,6 +414,12 @@ fn test_decrypt_matches_encrypt_validation_behavior() {
         passphrase: "pass".to_string(),
         output_dir: Some(non_existent.to_string_lossy().to_string()),
         force_overwrite: None,
+        path_limit_strategy: None,
+        ownership_policy: None,
+        restore_filter: None,
+        io_priority: None,
+        session_ttl_minutes: None,
+        accept_last_attempt: None,
     };
 
     assert!(
@@ -406,6 +436,12 @@ fn test_decrypt_matches_encrypt_validation_behavior() {
         passphrase: "pass".to_string(),
         output_dir: Some(existing.to_string_lossy().to_string()),
         force_overwrite: None,
+        path_limit_strategy: None,
+        ownership_policy: None,
+        restore_filter: None,
+        io_priority: None,
+        session_ttl_minutes: None,
+        accept_last_attempt: None,
     };
 
     assert!(
@@ -444,6 +480,12 @@ fn test_timestamp_based_directory_pattern() {
         passphrase: "pass".to_string(),
         output_dir: Some(recovery_dir.to_string_lossy().to_string()),
         force_overwrite: None,
+        path_limit_strategy: None,
+        ownership_policy: None,
+        restore_filter: None,
+        io_priority: None,
+        session_ttl_minutes: None,
+        accept_last_attempt: None,
     };
 
     // Should validate successfully even though directory doesn't exist
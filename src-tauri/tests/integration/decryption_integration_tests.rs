@@ -8,13 +8,14 @@
 //! - Security validation for decryption operations
 
 use crate::common::cleanup::TestCleanup;
+use crate::common::fixtures::fixture;
 use crate::common::helpers::TestAssertions;
 use barqly_vault_lib::services::{
     crypto::infrastructure::{decrypt_data, encrypt_data},
     file::infrastructure::file_operations::{FileOpsConfig, FileSelection},
     key_management::passphrase::generate_keypair,
 };
-use std::{fs, path::PathBuf, thread, time::Duration};
+use std::{path::PathBuf, thread, time::Duration};
 use tempfile::TempDir;
 
 // ============================================================================
@@ -54,24 +55,8 @@ fn new() -> Result<Self, Box<dyn std::error::Error>> {
     }
 
     fn create_test_files(temp_dir: &TempDir) -> Result<Vec<PathBuf>, Box<dyn std::error::Error>> {
-        let mut test_files = Vec::new();
-
-        // Create various test files
-        let medium_content = "This is a medium test file with more content".repeat(10);
-        let large_content = "This is a large test file with much more content".repeat(100);
-        let file_contents = vec![
-            ("small.txt", "This is a small test file"),
-            ("medium.txt", &medium_content),
-            ("large.txt", &large_content),
-        ];
-
-        for (filename, content) in file_contents {
-            let file_path = temp_dir.path().join(filename);
-            fs::write(&file_path, content)?;
-            test_files.push(file_path);
-        }
-
-        Ok(test_files)
+        let tree = fixture("decryption-basic").build(temp_dir.path())?;
+        Ok(tree.files())
     }
 
     fn cleanup(&self) {
@@ -0,0 +1,124 @@
+//! Integration tests for `barqly-vault://` deep links
+//!
+//! Tests cover the path from a Finder Services link to an encryption request:
+//! - Decoding the link and resolving its vault
+//! - Presenting it to the UI (recorded instead of emitted)
+//! - Queueing a second link while the first awaits confirmation
+
+use barqly_vault_lib::services::shared::infrastructure::deep_link::{
+    DeepLinkError, DeepLinkNotifier, DeepLinkQueue, PendingDeepLink,
+};
+use std::path::Path;
+use std::sync::Mutex;
+use tempfile::TempDir;
+
+/// Records presented links in place of the app's event emitter
+#[derive(Default)]
+struct RecordingNotifier {
+    presented: Mutex<Vec<PendingDeepLink>>,
+}
+
+impl DeepLinkNotifier for RecordingNotifier {
+    fn present(&self, link: &PendingDeepLink) {
+        self.presented.lock().unwrap().push(link.clone());
+    }
+}
+
+impl RecordingNotifier {
+    fn presented_ids(&self) -> Vec<String> {
+        let presented = self.presented.lock().unwrap();
+        presented.iter().map(|l| l.request_id.clone()).collect()
+    }
+}
+
+fn encode(path: &Path) -> String {
+    url::form_urlencoded::byte_serialize(path.to_string_lossy().as_bytes()).collect()
+}
+
+fn resolve(requested: &str) -> Option<String> {
+    (requested == "Family Docs").then(|| "vault-family".to_string())
+}
+
+#[test]
+fn test_finder_link_becomes_encryption_request() {
+    let temp_dir = TempDir::new().unwrap();
+    let first = temp_dir.path().join("passport scan.pdf");
+    let second = temp_dir.path().join("will.docx");
+    std::fs::write(&first, b"scan").unwrap();
+    std::fs::write(&second, b"will").unwrap();
+
+    let queue = DeepLinkQueue::new();
+    let notifier = RecordingNotifier::default();
+    let link = format!(
+        "barqly-vault://encrypt?paths={}&paths={}&vault=Family%20Docs",
+        encode(&first),
+        encode(&second)
+    );
+
+    let pending = queue.submit(&link, resolve, &notifier).unwrap();
+    assert_eq!(pending.vault_id.as_deref(), Some("vault-family"));
+    assert_eq!(notifier.presented_ids(), [pending.request_id.clone()]);
+
+    let confirmed = queue.confirm(&pending.request_id, None).unwrap();
+    assert_eq!(confirmed.vault_id, "vault-family");
+    assert_eq!(
+        confirmed.paths,
+        [first.display().to_string(), second.display().to_string()]
+    );
+
+    assert_eq!(queue.finish(&pending.request_id, &notifier).unwrap(), None);
+    assert!(queue.current().is_none());
+}
+
+#[test]
+fn test_second_link_waits_for_the_first() {
+    let temp_dir = TempDir::new().unwrap();
+    let file = temp_dir.path().join("notes.txt");
+    std::fs::write(&file, b"notes").unwrap();
+
+    let queue = DeepLinkQueue::new();
+    let notifier = RecordingNotifier::default();
+    let with_vault = format!(
+        "barqly-vault://encrypt?paths={}&vault=Family%20Docs",
+        encode(&file)
+    );
+    let without_vault = format!("barqly-vault://encrypt?paths={}", encode(&file));
+
+    let first = queue.submit(&with_vault, resolve, &notifier).unwrap();
+    let second = queue.submit(&without_vault, resolve, &notifier).unwrap();
+
+    // The second link neither replaces nor interrupts the first
+    assert_eq!(queue.current().unwrap().request_id, first.request_id);
+    assert_eq!(queue.waiting(), 1);
+    assert_eq!(notifier.presented_ids(), [first.request_id.clone()]);
+    assert!(matches!(
+        queue.confirm(&second.request_id, None),
+        Err(DeepLinkError::NotPending(_))
+    ));
+
+    let next = queue.finish(&first.request_id, &notifier).unwrap().unwrap();
+    assert_eq!(next.request_id, second.request_id);
+    assert_eq!(notifier.presented_ids().len(), 2);
+
+    // Without a vault in the link the user has to choose one
+    assert_eq!(next.vault_id, None);
+    assert!(matches!(
+        queue.confirm(&next.request_id, None),
+        Err(DeepLinkError::NoVaultChosen)
+    ));
+    let confirmed = queue
+        .confirm(&next.request_id, Some("vault-chosen".to_string()))
+        .unwrap();
+    assert_eq!(confirmed.vault_id, "vault-chosen");
+}
+
+#[test]
+fn test_rejected_link_is_not_queued() {
+    let queue = DeepLinkQueue::new();
+    let notifier = RecordingNotifier::default();
+
+    let result = queue.submit("javascript:alert(document.cookie)", resolve, &notifier);
+    assert!(matches!(result, Err(DeepLinkError::UnsupportedScheme(_))));
+    assert!(queue.current().is_none());
+    assert!(notifier.presented_ids().is_empty());
+}
@@ -8,6 +8,7 @@
 //! - Error handling and edge cases
 
 use crate::common::cleanup::TestCleanup;
+use crate::common::fixtures::{FixtureBuilder, FixtureTree, fixture};
 use crate::common::helpers::{PerformanceHelper, TestAssertions};
 use barqly_vault_lib::{
     commands::{
@@ -79,22 +80,12 @@ fn create_test_file(&mut self, name: &str, content: &str) -> std::path::PathBuf
         file_path
     }
 
-    fn create_test_folder_with_files(
-        &mut self,
-        folder_name: &str,
-        files: &[(&str, &str)],
-    ) -> std::path::PathBuf {
-        let folder_path = self.temp_dir.path().join(folder_name);
-        fs::create_dir_all(&folder_path).expect("Failed to create test folder");
-
-        for (file_name, content) in files {
-            let file_path = folder_path.join(file_name);
-            let mut file = fs::File::create(&file_path).expect("Failed to create test file");
-            file.write_all(content.as_bytes())
-                .expect("Failed to write test content");
-        }
-
-        folder_path
+    fn create_fixture_folder(&mut self, folder_name: &str, builder: FixtureBuilder) -> FixtureTree {
+        let tree = builder
+            .build(&self.temp_dir.path().join(folder_name))
+            .expect("Failed to build fixture tree");
+        self.test_files.extend(tree.files());
+        tree
     }
 
     fn setup_test_key(&self) -> String {
@@ -140,6 +131,7 @@ fn should_complete_encrypt_files_workflow_successfully() {
         ],
         output_name: Some("test_encrypted.age".to_string()),
         output_path: None,
+        contact_ids: vec![],
     };
 
     // When: Validating input
@@ -164,13 +156,11 @@ fn should_handle_encrypt_files_with_folder_selection() {
     setup_test_environment();
     // Given: Test environment with folder containing files
     let mut env = Task3IntegrationTestEnv::new();
-    let folder_path = env.create_test_folder_with_files(
-        "bitcoin_backup",
-        &[
-            ("wallet.dat", "wallet data"),
-            ("descriptor.txt", "descriptor data"),
-            ("keys.json", r#"{"key": "value"}"#),
-        ],
+    let tree = env.create_fixture_folder("bitcoin_backup", fixture("encryption-basic"));
+    let folder_path = tree.root.clone();
+    assert!(
+        !tree.files().is_empty(),
+        "Fixture folder should contain files"
     );
 
     let key_id = env.setup_test_key();
@@ -180,6 +170,7 @@ fn should_handle_encrypt_files_with_folder_selection() {
         file_paths: vec![folder_path.to_string_lossy().to_string()],
         output_name: None, // Use default naming
         output_path: None,
+        contact_ids: vec![],
     };
 
     // When: Validating input
@@ -209,6 +200,7 @@ fn should_handle_encrypt_files_key_not_found_error() {
         file_paths: vec![file1.to_string_lossy().to_string()],
         output_name: None,
         output_path: None,
+        contact_ids: vec![],
     };
 
     // When: Validating input
@@ -258,13 +250,9 @@ fn should_create_manifest_for_multiple_files_successfully() {
 fn should_create_manifest_for_single_folder_successfully() {
     // Given: Test environment with folder containing files
     let mut env = Task3IntegrationTestEnv::new();
-    let folder_path = env.create_test_folder_with_files(
-        "documents",
-        &[
-            ("doc1.pdf", "document 1 content"),
-            ("doc2.txt", "document 2 content"),
-        ],
-    );
+    let folder_path = env
+        .create_fixture_folder("documents", FixtureBuilder::new(258).file_count(2))
+        .root;
 
     let file_paths = [folder_path.to_string_lossy().to_string()];
 
@@ -379,6 +367,7 @@ fn should_complete_full_encryption_workflow_integration() {
         ],
         output_name: Some("workflow_test.age".to_string()),
         output_path: None,
+        contact_ids: vec![],
     };
 
     // Step 2: Prepare create_manifest input
@@ -443,6 +432,7 @@ fn should_handle_invalid_file_paths_in_encryption_workflow() {
         ],
         output_name: None,
         output_path: None,
+        contact_ids: vec![],
     };
 
     // When: Validating input
@@ -466,17 +456,19 @@ fn should_handle_large_file_list_in_encryption_workflow() {
     let key_id = env.setup_test_key();
 
     // Create many test files
-    let mut file_paths = Vec::new();
-    for i in 0..100 {
-        let file = env.create_test_file(&format!("file_{i}.txt"), &format!("content {i}"));
-        file_paths.push(file.to_string_lossy().to_string());
-    }
+    let tree = env.create_fixture_folder("many", FixtureBuilder::new(462).file_count(100));
+    let file_paths: Vec<String> = tree
+        .files()
+        .iter()
+        .map(|file| file.to_string_lossy().to_string())
+        .collect();
 
     let input = EncryptDataInput {
         key_id: key_id.clone(),
         file_paths: file_paths.clone(),
         output_name: None,
         output_path: None,
+        contact_ids: vec![],
     };
 
     // When: Validating input
@@ -506,6 +498,7 @@ fn should_validate_encryption_workflow_performance() {
                 file_paths: vec![format!("/path/to/file_{i}.txt")],
                 output_name: None,
                 output_path: None,
+                contact_ids: vec![],
             };
             let _ = input.validate();
         }
@@ -8,6 +8,9 @@
 //! - Error handling and edge cases
 //! - Performance validation with realistic file sizes
 
+use crate::common::fixtures::{
+    FixtureBuilder, FixtureTree, SizeDistribution, fixture, is_case_sensitive,
+};
 use crate::common::helpers::TestAssertions;
 use barqly_vault_lib::services::file::infrastructure::file_operations::{
     FileOpsConfig, FileSelection, create_archive, create_manifest_for_archive, create_staging_area,
@@ -48,34 +51,12 @@ fn create_test_file(&mut self, name: &str, content: &str) -> std::path::PathBuf
         file_path
     }
 
-    fn create_test_folder(&mut self, name: &str) -> std::path::PathBuf {
-        let folder_path = self.temp_dir.path().join(name);
-        fs::create_dir_all(&folder_path).expect("Failed to create test folder");
-        self.test_folders.push(folder_path.clone());
-        folder_path
-    }
-
-    fn create_test_folder_with_files(
-        &mut self,
-        folder_name: &str,
-        files: &[(&str, &str)],
-    ) -> std::path::PathBuf {
-        let folder_path = self.create_test_folder(folder_name);
-
-        for (file_name, content) in files {
-            let file_path = folder_path.join(file_name);
-
-            // Create parent directories if needed
-            if let Some(parent) = file_path.parent() {
-                fs::create_dir_all(parent).expect("Failed to create parent directory");
-            }
-
-            let mut file = fs::File::create(&file_path).expect("Failed to create test file");
-            file.write_all(content.as_bytes())
-                .expect("Failed to write test content");
-        }
-
-        folder_path
+    fn create_fixture_folder(&mut self, name: &str, builder: FixtureBuilder) -> FixtureTree {
+        let tree = builder
+            .build(&self.temp_dir.path().join(name))
+            .expect("Failed to build fixture tree");
+        self.test_folders.push(tree.root.clone());
+        tree
     }
 
     fn path(&self) -> &std::path::Path {
@@ -160,16 +141,10 @@ fn should_complete_file_encryption_workflow_successfully() {
 fn should_complete_folder_encryption_workflow_successfully() {
     // Given: Test folder with files and configuration
     let mut env = FileOpsTestEnv::new();
-    let folder_path = env.create_test_folder_with_files(
-        "bitcoin_data",
-        &[
-            ("wallet.dat", "wallet content"),
-            ("descriptor.txt", "descriptor content"),
-            ("subfolder/config.json", r#"{"network": "mainnet"}"#),
-        ],
-    );
+    let tree = env.create_fixture_folder("bitcoin_data", fixture("file-ops-tree"));
+    let expected_files = tree.relative_files();
 
-    let selection = FileSelection::Folder(folder_path);
+    let selection = FileSelection::Folder(tree.root.clone());
     let config = FileOpsConfig::default();
 
     // When: Validating selection
@@ -189,8 +164,8 @@ fn should_complete_folder_encryption_workflow_successfully() {
     // Then: Archive should be created with correct properties
     assert!(archive_path.exists(), "Folder archive file should exist");
     assert!(
-        archive_operation.file_count >= 3,
-        "Archive should contain at least 3 files"
+        archive_operation.file_count >= expected_files.len(),
+        "Archive should contain every fixture file"
     );
     assert!(
         archive_operation.total_size > 0,
@@ -204,36 +179,62 @@ fn should_complete_folder_encryption_workflow_successfully() {
         "Folder archive extraction should succeed",
     );
 
-    // Then: Folder structure should be preserved
+    // Then: Folder structure and contents should be preserved
     assert!(
-        extracted_files.len() >= 3,
-        "Should extract at least 3 files"
+        extracted_files.len() >= expected_files.len(),
+        "Should extract every fixture file"
     );
-    assert!(extract_dir.exists(), "Extraction directory should exist");
+    assert_tree_extracted(&tree, &extract_dir.join("bitcoin_data"));
+}
 
-    let extracted_bitcoin_data = extract_dir.join("bitcoin_data");
-    assert!(
-        extracted_bitcoin_data.exists(),
-        "Bitcoin data folder should exist"
-    );
-    assert!(
-        extracted_bitcoin_data.join("wallet.dat").exists(),
-        "Wallet file should exist"
-    );
-    assert!(
-        extracted_bitcoin_data.join("descriptor.txt").exists(),
-        "Descriptor file should exist"
-    );
-    assert!(
-        extracted_bitcoin_data.join("subfolder").exists(),
-        "Subfolder should exist"
+#[rstest]
+#[case("edge-zero-byte")]
+#[case("edge-unicode")]
+#[case("edge-deep-paths")]
+#[case("edge-case-collision")]
+fn should_round_trip_edge_case_fixture_sets(#[case] set_name: &str) {
+    // Given: An edge-case fixture tree
+    let env = FileOpsTestEnv::new();
+    if set_name == "edge-case-collision" && !is_case_sensitive(env.path()).unwrap() {
+        // Case-colliding names can't coexist on this filesystem
+        return;
+    }
+    let tree = fixture(set_name)
+        .build(&env.path().join(set_name))
+        .expect("Failed to build fixture tree");
+
+    let selection = FileSelection::Folder(tree.root.clone());
+    let config = FileOpsConfig::default();
+
+    // When: Archiving and extracting it
+    let archive_path = env.path().join(format!("{set_name}.tar.gz"));
+    TestAssertions::assert_ok(
+        create_archive(&selection, &archive_path, &config),
+        &format!("Archive creation should succeed for {set_name}"),
     );
-    assert!(
-        extracted_bitcoin_data
-            .join("subfolder/config.json")
-            .exists(),
-        "Config file in subfolder should exist"
+    let extract_dir = env.path().join("extracted");
+    TestAssertions::assert_ok(
+        extract_archive(&archive_path, &extract_dir, &config),
+        &format!("Extraction should succeed for {set_name}"),
     );
+
+    // Then: Every file comes back byte-for-byte
+    assert_tree_extracted(&tree, &extract_dir.join(set_name));
+}
+
+/// Assert every regular fixture file exists under `extracted` with its bytes
+fn assert_tree_extracted(tree: &FixtureTree, extracted: &std::path::Path) {
+    for entry in tree.entries.iter().filter(|e| e.is_file()) {
+        let path = extracted.join(&entry.path);
+        let content = fs::read(&path)
+            .unwrap_or_else(|e| panic!("Missing extracted file {}: {e}", path.display()));
+        assert_eq!(
+            content,
+            entry.bytes(),
+            "Content mismatch for {}",
+            entry.path.display()
+        );
+    }
 }
 
 // ============================================================================
@@ -328,10 +329,15 @@ fn should_handle_empty_selection_gracefully() {
 fn should_handle_large_files_successfully(#[case] file_size: usize, #[case] test_name: &str) {
     // Given: Large test file
     let mut env = FileOpsTestEnv::new();
-    let large_content: String = (0..file_size)
-        .map(|i| (i % 26 + 97) as u8 as char)
-        .collect();
-    let large_file = env.create_test_file(&format!("large_{test_name}.dat"), &large_content);
+    let tree = env.create_fixture_folder(
+        test_name,
+        FixtureBuilder::new(file_size as u64)
+            .file_count(1)
+            .sizes(SizeDistribution::Fixed(file_size as u64)),
+    );
+    let large_entry = &tree.entries[0];
+    let large_file = tree.root.join(&large_entry.path);
+    let file_name = large_entry.path.file_name().unwrap().to_owned();
 
     let selection = FileSelection::Files(vec![large_file]);
     let config = FileOpsConfig::default();
@@ -365,13 +371,13 @@ fn should_handle_large_files_successfully(#[case] file_size: usize, #[case] test
     assert_eq!(extracted_files.len(), 1, "Should extract 1 file");
     assert!(extract_dir.exists(), "Extraction directory should exist");
 
-    let extracted_file = extract_dir.join(format!("large_{test_name}.dat"));
+    let extracted_file = extract_dir.join(file_name);
     assert!(extracted_file.exists(), "Extracted large file should exist");
 
-    let extracted_content =
-        fs::read_to_string(&extracted_file).expect("Should read large file content");
+    let extracted_content = fs::read(&extracted_file).expect("Should read large file content");
     assert_eq!(
-        extracted_content, large_content,
+        extracted_content,
+        large_entry.bytes(),
         "Large file content should match"
     );
 }
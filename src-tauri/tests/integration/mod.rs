@@ -9,13 +9,17 @@
 // Test runner files are allowed to use println! for test reporting
 #![allow(clippy::disallowed_macros)]
 
+pub mod armored_archive_tests;
 pub mod crypto_integration_tests;
 pub mod decrypt_directory_tests;
 pub mod decryption_integration_tests;
+pub mod deep_link_tests;
 pub mod encryption_integration_tests;
 pub mod file_ops_integration_tests;
 pub mod logging_integration_tests;
 pub mod output_path_integration_tests;
+pub mod salvage_decrypt_tests;
+pub mod window_session_tests;
 pub mod workflows;
 
 use super::common::{TestSuite, TestSuiteConfig};
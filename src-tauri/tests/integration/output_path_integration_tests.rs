@@ -46,6 +46,7 @@ fn test_encrypt_input_with_custom_output_path() {
             ],
             output_name: Some("custom_encrypted".to_string()),
             output_path: Some(output_dir.to_string_lossy().to_string()),
+            contact_ids: vec![],
         };
 
         // Verify output path is set correctly
@@ -81,6 +82,7 @@ fn test_encrypt_input_without_output_path_uses_default() {
             file_paths: vec![test_file.to_string_lossy().to_string()],
             output_name: Some("default_location".to_string()),
             output_path: None, // No output path specified
+            contact_ids: vec![],
         };
 
         // Verify output path is None (will use current directory)
@@ -119,6 +121,7 @@ fn test_encrypt_input_with_relative_output_path() {
             file_paths: vec![test_file.to_string_lossy().to_string()],
             output_name: Some("relative_test".to_string()),
             output_path: Some(output_dir.to_string_lossy().to_string()),
+            contact_ids: vec![],
         };
 
         // The path should be accepted
@@ -151,6 +154,7 @@ fn test_encrypt_input_with_absolute_output_path() {
             file_paths: vec![test_file.to_string_lossy().to_string()],
             output_name: Some("absolute_test".to_string()),
             output_path: Some(absolute_output.to_string_lossy().to_string()),
+            contact_ids: vec![],
         };
 
         assert!(
@@ -181,6 +185,7 @@ fn test_encrypt_input_with_non_existent_output_path() {
             file_paths: vec![test_file.to_string_lossy().to_string()],
             output_name: Some("test_output".to_string()),
             output_path: Some(non_existent.to_string_lossy().to_string()),
+            contact_ids: vec![],
         };
 
         // Directory should not exist initially
@@ -214,6 +219,7 @@ fn test_encrypt_input_with_spaces_in_output_path() {
             file_paths: vec![test_file.to_string_lossy().to_string()],
             output_name: Some("file with spaces".to_string()),
             output_path: Some(dir_with_spaces.to_string_lossy().to_string()),
+            contact_ids: vec![],
         };
 
         assert!(
@@ -261,6 +267,7 @@ fn test_encrypt_input_with_permission_issues() {
             file_paths: vec![test_file.to_string_lossy().to_string()],
             output_name: Some("will_fail".to_string()),
             output_path: Some(readonly_dir.to_string_lossy().to_string()),
+            contact_ids: vec![],
         };
 
         // Input validation may pass, permission error would occur at runtime
@@ -289,6 +296,7 @@ fn test_path_joining_regression() {
             file_paths: vec![test_file.to_string_lossy().to_string()],
             output_name: Some(output_name.to_string()),
             output_path: None, // Will use current directory
+            contact_ids: vec![],
         };
 
         // The bug would create a path like:
@@ -357,6 +365,7 @@ fn test_output_directory_with_unicode_characters() {
             file_paths: vec![test_file.to_string_lossy().to_string()],
             output_name: Some("unicode_test".to_string()),
             output_path: Some(unicode_dir.to_string_lossy().to_string()),
+            contact_ids: vec![],
         };
 
         assert!(unicode_dir.exists(), "Unicode directory should exist");
@@ -394,6 +403,7 @@ fn test_output_directory_deeply_nested() {
             file_paths: vec![test_file.to_string_lossy().to_string()],
             output_name: Some("deep_test".to_string()),
             output_path: Some(deep_path.to_string_lossy().to_string()),
+            contact_ids: vec![],
         };
 
         assert!(deep_path.exists(), "Deep nested path should exist");
@@ -421,6 +431,7 @@ fn test_output_directory_windows_drive_root() {
             file_paths: vec!["C:\\test.txt".to_string()],
             output_name: Some("root_test".to_string()),
             output_path: Some(drive_root.to_string()),
+            contact_ids: vec![],
         };
 
         assert!(
@@ -454,6 +465,7 @@ fn test_output_path_with_dot_notation() {
                 file_paths: vec![test_file.to_string_lossy().to_string()],
                 output_name: Some("dot_test".to_string()),
                 output_path: Some(dot_path.to_string()),
+                contact_ids: vec![],
             };
 
             assert!(
@@ -0,0 +1,251 @@
+//! Integration tests for salvaging damaged archives
+//!
+//! Tests corrupt specific STREAM chunks of an encrypted backup bundle and
+//! check which files salvage reports as recovered, partial or lost:
+//! - Damage after the first files leaves them recovered and verified
+//! - A file spanning the damaged chunk is recovered up to the damage
+//! - Files after the damage are named as lost from the manifest
+//! - Damage to the first chunk loses everything, including the manifest
+
+use barqly_vault_lib::services::crypto::application::services::{
+    SalvageDecryptionService, SalvageReport,
+};
+use barqly_vault_lib::services::crypto::infrastructure::{STREAM_CHUNK_SIZE, encrypt_data};
+use barqly_vault_lib::services::file::infrastructure::file_operations::{HashAlgorithm, RawPath};
+use barqly_vault_lib::services::key_management::passphrase::generate_keypair;
+use barqly_vault_lib::services::shared::infrastructure::DeviceInfo;
+use barqly_vault_lib::services::vault::infrastructure::persistence::metadata::{
+    VaultFileEntry, VaultMetadata,
+};
+use flate2::Compression;
+use flate2::write::GzEncoder;
+use sha2::{Digest, Sha256};
+use tar::{Builder, Header};
+use tempfile::TempDir;
+
+/// Archive layout: manifest, then a.bin (~0-52K), b.bin (~53K-253K) and
+/// c.bin (~254K-354K) in the TAR.GZ payload, i.e. STREAM chunks 0-5
+const FILES: [(&str, usize); 3] = [("a.bin", 50_000), ("b.bin", 200_000), ("c.bin", 100_000)];
+
+/// Incompressible content so payload offsets track tar offsets
+fn random_bytes(len: usize, seed: u64) -> Vec<u8> {
+    let mut state = seed | 1;
+    (0..len)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state as u8
+        })
+        .collect()
+}
+
+fn manifest(files: &[(String, Vec<u8>)]) -> VaultMetadata {
+    let device = DeviceInfo {
+        machine_id: "test-machine".to_string(),
+        machine_label: "test-laptop".to_string(),
+        created_at: chrono::Utc::now(),
+        app_version: "2.0.0".to_string(),
+    };
+    let entries: Vec<VaultFileEntry> = files
+        .iter()
+        .map(|(name, content)| VaultFileEntry {
+            path: name.clone(),
+            raw_path: RawPath::from_display(name),
+            lossy_name: false,
+            size: content.len() as u64,
+            sha256: hex::encode(Sha256::digest(content)),
+            hash_algorithm: HashAlgorithm::Sha256,
+            digest: None,
+            ownership: None,
+            content_type: None,
+            content_type_mismatch: false,
+        })
+        .collect();
+    let total_size = entries.iter().map(|e| e.size).sum();
+    VaultMetadata::new(
+        "vault-001".to_string(),
+        "Family Docs".to_string(),
+        None,
+        "Family-Docs".to_string(),
+        &device,
+        None,
+        vec![],
+        entries,
+        files.len(),
+        total_size,
+    )
+}
+
+fn append(builder: &mut Builder<GzEncoder<Vec<u8>>>, path: &str, content: &[u8]) {
+    let mut header = Header::new_gnu();
+    header.set_size(content.len() as u64);
+    header.set_mode(0o644);
+    header.set_cksum();
+    builder.append_data(&mut header, path, content).unwrap();
+}
+
+/// Encrypted bundle plus its manifest and private key
+struct Fixture {
+    encrypted: Vec<u8>,
+    manifest: VaultMetadata,
+    private_key: barqly_vault_lib::services::crypto::PrivateKey,
+    chunks_start: usize,
+}
+
+fn fixture() -> Fixture {
+    let files: Vec<(String, Vec<u8>)> = FILES
+        .iter()
+        .zip(1u64..)
+        .map(|((name, len), seed)| (name.to_string(), random_bytes(*len, seed)))
+        .collect();
+    let manifest = manifest(&files);
+
+    let mut builder = Builder::new(GzEncoder::new(Vec::new(), Compression::default()));
+    append(
+        &mut builder,
+        "Family-Docs.manifest",
+        &serde_json::to_vec(&manifest).unwrap(),
+    );
+    for (name, content) in &files {
+        append(&mut builder, &format!("docs/{}", name), content);
+    }
+    let payload = builder.into_inner().unwrap().finish().unwrap();
+
+    let keypair = generate_keypair().unwrap();
+    let encrypted = encrypt_data(&payload, &keypair.public_key).unwrap();
+    // STREAM chunks are 64 KiB + 16-byte tag and end the file
+    let chunk_count = payload.len().div_ceil(STREAM_CHUNK_SIZE);
+    let chunks_start = encrypted.len() - payload.len() - chunk_count * 16;
+
+    Fixture {
+        encrypted,
+        manifest,
+        private_key: keypair.private_key,
+        chunks_start,
+    }
+}
+
+fn corrupt_chunk(fixture: &Fixture, index: usize) -> Vec<u8> {
+    let mut damaged = fixture.encrypted.clone();
+    damaged[fixture.chunks_start + index * (STREAM_CHUNK_SIZE + 16) + 1000] ^= 0xff;
+    damaged
+}
+
+fn names(paths: impl Iterator<Item = String>) -> Vec<String> {
+    paths
+        .map(|p| p.rsplit('/').next().unwrap().to_string())
+        .collect()
+}
+
+fn salvage(fixture: &Fixture, data: &[u8], output: Option<&TempDir>) -> SalvageReport {
+    SalvageDecryptionService::new()
+        .salvage(
+            data,
+            &fixture.private_key,
+            output.map(|dir| dir.path()),
+            None,
+        )
+        .unwrap()
+}
+
+#[test]
+fn test_intact_archive_recovers_everything() {
+    let fixture = fixture();
+    let report = salvage(&fixture, &fixture.encrypted, None);
+
+    assert!(report.is_intact());
+    assert!(report.lost_list_complete);
+    assert_eq!(report.chunk_count, 6);
+    assert_eq!(
+        names(report.recovered.iter().map(|f| f.path.clone())),
+        ["a.bin", "b.bin", "c.bin"]
+    );
+    assert!(report.recovered.iter().all(|f| f.hash_verified));
+    assert!(!report.extracted);
+}
+
+#[test]
+fn test_damaged_middle_chunk_classifies_files() {
+    let fixture = fixture();
+    let damaged = corrupt_chunk(&fixture, 2);
+    let output = TempDir::new().unwrap();
+
+    let report = salvage(&fixture, &damaged, Some(&output));
+
+    assert_eq!(report.damaged_chunk_count, 1);
+    let chunk = STREAM_CHUNK_SIZE as u64;
+    assert_eq!(report.damaged_ranges[0].start, 2 * chunk);
+    assert_eq!(report.damaged_ranges[0].end, 3 * chunk);
+
+    assert_eq!(
+        names(report.recovered.iter().map(|f| f.path.clone())),
+        ["a.bin"]
+    );
+    assert!(report.recovered[0].hash_verified);
+
+    assert_eq!(
+        names(report.partial.iter().map(|f| f.path.clone())),
+        ["b.bin"]
+    );
+    let partial = &report.partial[0];
+    assert_eq!(partial.expected_size, 200_000);
+    assert!(partial.recovered_bytes > 0 && partial.recovered_bytes < 2 * chunk);
+
+    assert_eq!(names(report.lost.iter().map(|f| f.path.clone())), ["c.bin"]);
+    assert_eq!(report.lost[0].size, 100_000);
+
+    // Recovered files are intact; the truncated one can't pass for the real file
+    let docs = output.path().join("docs");
+    let a = std::fs::read(docs.join("a.bin")).unwrap();
+    assert_eq!(a, random_bytes(50_000, 1));
+    let b = std::fs::read(docs.join("b.bin.partial")).unwrap();
+    assert_eq!(b.len() as u64, partial.recovered_bytes);
+    assert_eq!(b[..], random_bytes(200_000, 2)[..b.len()]);
+    assert!(!docs.join("b.bin").exists());
+    assert!(!docs.join("c.bin").exists());
+    assert!(report.extracted);
+}
+
+#[test]
+fn test_damage_in_last_file_keeps_earlier_files() {
+    let fixture = fixture();
+    let report = salvage(&fixture, &corrupt_chunk(&fixture, 4), None);
+
+    assert_eq!(
+        names(report.recovered.iter().map(|f| f.path.clone())),
+        ["a.bin", "b.bin"]
+    );
+    assert_eq!(
+        names(report.partial.iter().map(|f| f.path.clone())),
+        ["c.bin"]
+    );
+    assert!(report.lost.is_empty());
+}
+
+#[test]
+fn test_damaged_first_chunk_loses_everything() {
+    let fixture = fixture();
+    let damaged = corrupt_chunk(&fixture, 0);
+
+    // The embedded manifest is lost too, so lost files can't be named
+    let report = salvage(&fixture, &damaged, None);
+    assert!(report.recovered.is_empty() && report.partial.is_empty());
+    assert!(report.lost.is_empty());
+    assert!(!report.lost_list_complete);
+
+    // A local copy of the manifest names them
+    let report = SalvageDecryptionService::new()
+        .salvage(
+            &damaged,
+            &fixture.private_key,
+            None,
+            Some(&fixture.manifest),
+        )
+        .unwrap();
+    assert!(report.lost_list_complete);
+    assert_eq!(
+        names(report.lost.iter().map(|f| f.path.clone())),
+        ["a.bin", "b.bin", "c.bin"]
+    );
+}
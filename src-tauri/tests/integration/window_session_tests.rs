@@ -0,0 +1,108 @@
+//! Integration tests for multi-window session state
+//!
+//! Two simulated windows share the app's operation state but keep their own
+//! current vault:
+//! - Selecting a vault in one window leaves the other untouched
+//! - A running operation reads the same from both windows
+//! - Closing a window hands the default vault back to the other
+
+use barqly_vault_lib::services::shared::infrastructure::WindowSessions;
+use barqly_vault_lib::services::shared::infrastructure::progress::{
+    OperationKind, active_operations, begin_operation, get_global_progress, update_global_progress,
+};
+use barqly_vault_lib::types::{IoPriority, ProgressUpdate};
+
+/// A window as the commands see it: its label and the shared session registry
+struct SimulatedWindow<'a> {
+    label: &'static str,
+    sessions: &'a WindowSessions,
+}
+
+impl SimulatedWindow<'_> {
+    fn select(&self, vault_id: &str) {
+        self.sessions.set_current_vault(self.label, vault_id);
+    }
+
+    fn current_vault(&self) -> Option<String> {
+        self.sessions.current_vault(self.label)
+    }
+
+    /// What the window's progress poll returns for `operation_id`
+    fn poll_progress(&self, operation_id: &str) -> Option<ProgressUpdate> {
+        get_global_progress(operation_id)
+    }
+}
+
+fn progress(operation_id: &str, progress: f32) -> ProgressUpdate {
+    ProgressUpdate {
+        operation_id: operation_id.to_string(),
+        progress,
+        message: "Encrypting files...".to_string(),
+        details: None,
+        timestamp: chrono::Utc::now(),
+        estimated_time_remaining: None,
+        io_priority: IoPriority::Normal,
+        trace_id: None,
+    }
+}
+
+#[test]
+fn test_windows_keep_independent_current_vaults() {
+    let sessions = WindowSessions::new();
+    let main = SimulatedWindow {
+        label: "main",
+        sessions: &sessions,
+    };
+    let second = SimulatedWindow {
+        label: "vault-window-2",
+        sessions: &sessions,
+    };
+
+    main.select("vault-family");
+    assert_eq!(main.current_vault().as_deref(), Some("vault-family"));
+    assert_eq!(second.current_vault(), None);
+
+    second.select("vault-business");
+    assert_eq!(main.current_vault().as_deref(), Some("vault-family"));
+    assert_eq!(second.current_vault().as_deref(), Some("vault-business"));
+
+    // Deep links have no window and follow the latest selection
+    assert_eq!(sessions.default_vault().as_deref(), Some("vault-business"));
+
+    sessions.close_window(second.label);
+    assert_eq!(sessions.default_vault().as_deref(), Some("vault-family"));
+}
+
+#[test]
+fn test_running_operation_is_visible_from_every_window() {
+    let sessions = WindowSessions::new();
+    let main = SimulatedWindow {
+        label: "main",
+        sessions: &sessions,
+    };
+    let second = SimulatedWindow {
+        label: "vault-window-2",
+        sessions: &sessions,
+    };
+    main.select("vault-family");
+    second.select("vault-business");
+
+    // Started from the main window
+    let operation_id = "encrypt_window_session_test";
+    let guard = begin_operation(OperationKind::Encryption).unwrap();
+    update_global_progress(operation_id, progress(operation_id, 0.4));
+
+    let seen_by_main = main.poll_progress(operation_id).unwrap();
+    let seen_by_second = second.poll_progress(operation_id).unwrap();
+    assert_eq!(seen_by_main.operation_id, seen_by_second.operation_id);
+    assert_eq!(seen_by_main.progress, seen_by_second.progress);
+    assert_eq!(seen_by_main.message, seen_by_second.message);
+    assert!(active_operations().contains(&OperationKind::Encryption));
+
+    // Selecting another vault elsewhere does not touch the running operation
+    second.select("vault-archive");
+    assert_eq!(second.poll_progress(operation_id).unwrap().progress, 0.4);
+    assert_eq!(main.current_vault().as_deref(), Some("vault-family"));
+
+    drop(guard);
+}
@@ -0,0 +1,148 @@
+//! Scale test for archive manifests with hundreds of thousands of entries
+//!
+//! Verifies a synthetic 200k-entry manifest (the size of a large Maildir or
+//! photo library) and checks that streaming verification stays within a
+//! memory budget and a time bound. Allocations are counted per thread, so
+//! setup and other tests don't affect the figures.
+//!
+//! Run with:
+//! ```
+//! cargo test --test manifest_scale_test -- --nocapture
+//! ```
+
+use barqly_vault_lib::services::file::infrastructure::file_operations::archive_manifest::{
+    Manifest, read_manifest_summary, verify_manifest_file,
+};
+use barqly_vault_lib::services::file::infrastructure::file_operations::{
+    ArchiveOperation, FileInfo, FileOpsConfig,
+};
+use chrono::{DateTime, Utc};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+const ENTRY_COUNT: usize = 200_000;
+/// Peak heap growth allowed while verifying, beyond the extracted file list
+/// itself. Loading the manifest whole takes several times this.
+const VERIFY_MEMORY_LIMIT: usize = 16 * 1024 * 1024;
+/// Peak heap growth allowed for an indexed summary
+const SUMMARY_MEMORY_LIMIT: usize = 64 * 1024;
+
+struct CountingAllocator;
+
+thread_local! {
+    static CURRENT: Cell<usize> = const { Cell::new(0) };
+    static PEAK: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = unsafe { System.alloc(layout) };
+        if !ptr.is_null() {
+            record(layout.size() as isize);
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) };
+        record(-(layout.size() as isize));
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new_ptr = unsafe { System.realloc(ptr, layout, new_size) };
+        if !new_ptr.is_null() {
+            record(new_size as isize - layout.size() as isize);
+        }
+        new_ptr
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+fn record(delta: isize) {
+    // try_with: thread-locals may already be gone while a thread shuts down
+    let _ = CURRENT.try_with(|current| {
+        let now = current.get().saturating_add_signed(delta);
+        current.set(now);
+        let _ = PEAK.try_with(|peak| peak.set(peak.get().max(now)));
+    });
+}
+
+/// Peak heap growth on this thread while `f` runs
+fn measure_peak<T>(f: impl FnOnce() -> T) -> (T, usize) {
+    let start = CURRENT.with(Cell::get);
+    PEAK.with(|peak| peak.set(start));
+    let result = f();
+    (result, PEAK.with(Cell::get) - start)
+}
+
+fn synthetic_files() -> Vec<FileInfo> {
+    let modified = DateTime::from_timestamp(1_700_000_000, 0).unwrap();
+    (0..ENTRY_COUNT)
+        .map(|i| FileInfo {
+            path: PathBuf::from(format!("Maildir/cur/{:03}/{i:07}.eml", i % 512)),
+            size: (i % 4096) as u64 + 1,
+            modified,
+            hash: format!("{i:064x}"),
+            #[cfg(unix)]
+            permissions: 0o644,
+        })
+        .collect()
+}
+
+#[test]
+fn test_verify_200k_entry_manifest_within_budget() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let manifest_path = temp_dir.path().join("manifest.json");
+    let files = synthetic_files();
+    let archive_operation = ArchiveOperation {
+        archive_path: PathBuf::from("mail.tar.gz"),
+        manifest_path: None,
+        total_size: 0,
+        file_count: files.len(),
+        created: Utc::now(),
+        archive_hash: "0".repeat(64),
+    };
+    Manifest::new(&archive_operation, &files, &archive_operation.archive_path)
+        .unwrap()
+        .save(&manifest_path)
+        .unwrap();
+    let manifest_bytes = std::fs::metadata(&manifest_path).unwrap().len();
+
+    let (summary, summary_peak) = measure_peak(|| read_manifest_summary(&manifest_path));
+    let summary = summary.unwrap();
+    assert!(summary.indexed);
+    assert_eq!(summary.entry_count, ENTRY_COUNT);
+    assert!(
+        summary_peak < SUMMARY_MEMORY_LIMIT,
+        "summary peaked at {summary_peak} bytes"
+    );
+
+    let started = Instant::now();
+    let (verified, verify_peak) =
+        measure_peak(|| verify_manifest_file(&manifest_path, &files, &FileOpsConfig::default()));
+    let elapsed = started.elapsed();
+    verified.unwrap();
+
+    println!(
+        "{ENTRY_COUNT} entries, {manifest_bytes} byte manifest: verified in {elapsed:?}, \
+         peak {verify_peak} bytes"
+    );
+    assert!(
+        verify_peak < VERIFY_MEMORY_LIMIT,
+        "verification peaked at {verify_peak} bytes"
+    );
+    assert!((verify_peak as u64) < manifest_bytes / 2);
+
+    // Unoptimized builds are far slower; the bound is there to catch
+    // quadratic behaviour, not to benchmark
+    let time_limit = if cfg!(debug_assertions) {
+        Duration::from_secs(120)
+    } else {
+        Duration::from_secs(10)
+    };
+    assert!(elapsed < time_limit, "verification took {elapsed:?}");
+}
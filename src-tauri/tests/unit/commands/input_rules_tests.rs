@@ -0,0 +1,682 @@
+//! Field-level validation of command inputs
+//!
+//! Calls every command migrated to `input_rules!` with a canonical invalid
+//! input and checks the structured `ValidationFailed` error names the
+//! offending field and rule. Commands that need a window are checked through
+//! their input's `validate()`.
+
+use barqly_vault_lib::commands::crypto::{
+    AnalyzeEncryptedVaultRequest, AssessSalvageInput, BrowseArchiveInput,
+    CancelCleanupSessionInput, CheckDecryptionKeyInput, DecryptBatchInput, DecryptDataInput,
+    DecryptWithRecoverySharesInput, EncryptDataInput, EncryptFilesMultiInput,
+    ExtendCleanupSessionInput, GetProgressInput, RegenerateExternalManifestInput,
+    SalvageDecryptInput, StopBrowsingInput, VerifyManifestInput, VerifyToolIndependenceInput,
+    analyze_encrypted_vault, assess_salvage, browse_archive, cancel_cleanup_session,
+    check_decryption_key, decrypt_batch, decrypt_with_recovery_shares, extend_cleanup_session,
+    get_progress, regenerate_external_manifest, salvage_decrypt, stop_browsing, verify_manifest,
+    verify_tool_independence,
+};
+use barqly_vault_lib::commands::key_management::passphrase::{
+    AddPassphraseKeyRequest, CreateRecoverySharesRequest, GenerateKeyInput,
+    add_passphrase_key_to_vault, create_recovery_shares, generate_key,
+};
+use barqly_vault_lib::commands::key_management::unified_keys::{
+    GetVaultKeysRequest, RemoveKeyFromVaultRequest, UpdateKeyLabelRequest, get_vault_keys,
+    remove_key_from_vault, update_key_label,
+};
+use barqly_vault_lib::commands::key_management::{
+    AddRecipientRequest, AttachKeyToVaultRequest, DeactivateKeyRequest, DeleteKeyRequest,
+    ExportKeyRequest, ImportKeyFileRequest, RegisterYubiKeyForVaultParams, RelinkKeyFileRequest,
+    ReserveYubiKeySlotParams, RestoreKeyRequest, UpdateGlobalKeyLabelRequest, add_recipient,
+    attach_key_to_vault, deactivate_key, delete_key, export_key, import_key_file,
+    register_yubikey_for_vault, relink_key_file, reserve_yubikey_slot, restore_key,
+    update_global_key_label,
+};
+use barqly_vault_lib::commands::types::{CommandError, ErrorCode, ValidateInput, ValidationRule};
+use barqly_vault_lib::commands::vault::{
+    AssessVaultRiskRequest, CheckInRequest, DeleteVaultRequest, DismissNotificationRequest,
+    GetNotificationPreferencesRequest, GetProtectionStatusRequest, GetVaultHooksRequest,
+    GetVaultStatisticsHistoryRequest, ListArchivesRequest, ListVaultItemsRequest,
+    PurgeQuarantineRequest, RemoveVaultItemRequest, RestoreMetadataSnapshotRequest,
+    SearchArchivesRequest, SearchFilesRequest, SetArchiveImmutableRequest, SetCurrentVaultRequest,
+    TestHookRequest, UpdateArchiveCommentRequest, assess_vault_risk, check_in, delete_vault,
+    dismiss_notification, get_notification_preferences, get_protection_status, get_vault_hooks,
+    get_vault_statistics_history, list_archives, list_vault_items, purge_quarantine,
+    remove_vault_item, restore_metadata_snapshot, search_archives, search_files,
+    set_archive_immutable, test_hook, update_archive_comment,
+};
+
+const MISSING_VAULT: &str = "no-such-vault-0000";
+const MISSING_FILE: &str = "/no/such/dir/archive.age";
+
+/// Assert `result` is a `ValidationFailed` error for `field` breaking `rule`
+fn assert_invalid<T>(result: Result<T, Box<CommandError>>, field: &str, rule: ValidationRule) {
+    let Err(error) = result else {
+        panic!("invalid {field} should be rejected");
+    };
+    assert!(
+        matches!(error.code, ErrorCode::ValidationFailed),
+        "{field}: expected ValidationFailed, got {:?}",
+        error.code
+    );
+    let failure = error
+        .validation
+        .unwrap_or_else(|| panic!("{field}: error should name the field"));
+    assert_eq!(failure.field, field);
+    assert_eq!(failure.rule, rule, "{field}");
+    assert_eq!(failure.message_key, rule.message_key());
+}
+
+#[cfg(test)]
+mod vault_command_tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_vault_commands_reject_unknown_vault() {
+        assert_invalid(
+            list_archives(ListArchivesRequest {
+                vault_id: String::new(),
+                sort: None,
+                page: None,
+            })
+            .await,
+            "vault_id",
+            ValidationRule::ExistingVaultId,
+        );
+        assert_invalid(
+            search_archives(SearchArchivesRequest {
+                vault_id: MISSING_VAULT.to_string(),
+                query: "2024".to_string(),
+            })
+            .await,
+            "vault_id",
+            ValidationRule::ExistingVaultId,
+        );
+        assert_invalid(
+            update_archive_comment(UpdateArchiveCommentRequest {
+                vault_id: MISSING_VAULT.to_string(),
+                archive_id: "archive-1".to_string(),
+                comment: None,
+            })
+            .await,
+            "vault_id",
+            ValidationRule::ExistingVaultId,
+        );
+        assert_invalid(
+            set_archive_immutable(SetArchiveImmutableRequest {
+                vault_id: MISSING_VAULT.to_string(),
+                archive_id: "archive-1".to_string(),
+                immutable: true,
+                confirmation: None,
+            })
+            .await,
+            "vault_id",
+            ValidationRule::ExistingVaultId,
+        );
+        assert_invalid(
+            get_vault_hooks(GetVaultHooksRequest {
+                vault_id: MISSING_VAULT.to_string(),
+            })
+            .await,
+            "vault_id",
+            ValidationRule::ExistingVaultId,
+        );
+        assert_invalid(
+            test_hook(TestHookRequest {
+                vault_id: MISSING_VAULT.to_string(),
+                hook_id: "hook-1".to_string(),
+            })
+            .await,
+            "vault_id",
+            ValidationRule::ExistingVaultId,
+        );
+        assert_invalid(
+            list_vault_items(ListVaultItemsRequest {
+                vault_id: MISSING_VAULT.to_string(),
+            })
+            .await,
+            "vault_id",
+            ValidationRule::ExistingVaultId,
+        );
+        assert_invalid(
+            remove_vault_item(RemoveVaultItemRequest {
+                vault_id: MISSING_VAULT.to_string(),
+                item_id: "item-1".to_string(),
+            })
+            .await,
+            "vault_id",
+            ValidationRule::ExistingVaultId,
+        );
+        assert_invalid(
+            purge_quarantine(PurgeQuarantineRequest {
+                vault_id: MISSING_VAULT.to_string(),
+                older_than_days: 30,
+            })
+            .await,
+            "vault_id",
+            ValidationRule::ExistingVaultId,
+        );
+        assert_invalid(
+            get_notification_preferences(GetNotificationPreferencesRequest {
+                vault_id: MISSING_VAULT.to_string(),
+            })
+            .await,
+            "vault_id",
+            ValidationRule::ExistingVaultId,
+        );
+        assert_invalid(
+            assess_vault_risk(AssessVaultRiskRequest {
+                vault_id: MISSING_VAULT.to_string(),
+            })
+            .await,
+            "vault_id",
+            ValidationRule::ExistingVaultId,
+        );
+        assert_invalid(
+            get_protection_status(GetProtectionStatusRequest {
+                vault_id: MISSING_VAULT.to_string(),
+            })
+            .await,
+            "vault_id",
+            ValidationRule::ExistingVaultId,
+        );
+        assert_invalid(
+            get_vault_statistics_history(GetVaultStatisticsHistoryRequest {
+                vault_id: MISSING_VAULT.to_string(),
+                range: Default::default(),
+                max_points: None,
+            })
+            .await,
+            "vault_id",
+            ValidationRule::ExistingVaultId,
+        );
+        assert_invalid(
+            check_in(CheckInRequest {
+                vault_id: MISSING_VAULT.to_string(),
+                confirmation: Some("CHECK IN".to_string()),
+            })
+            .await,
+            "vault_id",
+            ValidationRule::ExistingVaultId,
+        );
+        assert_invalid(
+            delete_vault(DeleteVaultRequest {
+                vault_id: MISSING_VAULT.to_string(),
+                force: false,
+            })
+            .await,
+            "vault_id",
+            ValidationRule::ExistingVaultId,
+        );
+        assert_invalid(
+            SetCurrentVaultRequest {
+                vault_id: MISSING_VAULT.to_string(),
+            }
+            .validate(),
+            "vault_id",
+            ValidationRule::ExistingVaultId,
+        );
+    }
+
+    #[tokio::test]
+    async fn test_vault_commands_reject_invalid_fields() {
+        assert_invalid(
+            dismiss_notification(DismissNotificationRequest {
+                notification_id: "stale-backup".to_string(),
+                snooze_days: Some(0),
+            })
+            .await,
+            "snooze_days",
+            ValidationRule::InRange,
+        );
+        assert_invalid(
+            dismiss_notification(DismissNotificationRequest {
+                notification_id: " ".to_string(),
+                snooze_days: None,
+            })
+            .await,
+            "notification_id",
+            ValidationRule::NonEmptyId,
+        );
+        assert_invalid(
+            restore_metadata_snapshot(RestoreMetadataSnapshotRequest {
+                snapshot_id: String::new(),
+                confirmation: Some("RESTORE".to_string()),
+            })
+            .await,
+            "snapshot_id",
+            ValidationRule::NonEmptyId,
+        );
+        assert_invalid(
+            search_files(SearchFilesRequest {
+                query: " ".to_string(),
+                scope: None,
+            })
+            .await,
+            "query",
+            ValidationRule::NonEmpty,
+        );
+    }
+}
+
+#[cfg(test)]
+mod key_management_command_tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_key_commands_reject_empty_key_id() {
+        assert_invalid(
+            delete_key(DeleteKeyRequest {
+                key_id: String::new(),
+                reason: None,
+            })
+            .await,
+            "key_id",
+            ValidationRule::NonEmptyId,
+        );
+        assert_invalid(
+            restore_key(RestoreKeyRequest {
+                key_id: String::new(),
+            })
+            .await,
+            "key_id",
+            ValidationRule::NonEmptyId,
+        );
+        assert_invalid(
+            deactivate_key(DeactivateKeyRequest {
+                key_id: String::new(),
+                reason: None,
+                delete_immediately: None,
+            })
+            .await,
+            "key_id",
+            ValidationRule::NonEmptyId,
+        );
+        assert_invalid(
+            remove_key_from_vault(RemoveKeyFromVaultRequest {
+                vault_id: MISSING_VAULT.to_string(),
+                key_id: String::new(),
+            })
+            .await,
+            "vault_id",
+            ValidationRule::ExistingVaultId,
+        );
+    }
+
+    #[tokio::test]
+    async fn test_key_commands_reject_invalid_fields() {
+        assert_invalid(
+            export_key(ExportKeyRequest {
+                key_id: "key-1".to_string(),
+                destination_path: String::new(),
+            })
+            .await,
+            "destination_path",
+            ValidationRule::NonEmpty,
+        );
+        assert_invalid(
+            import_key_file(ImportKeyFileRequest {
+                file_path: String::new(),
+                passphrase: None,
+                override_label: None,
+                attach_to_vault: None,
+                validate_only: true,
+                auto_rename: false,
+            })
+            .await,
+            "file_path",
+            ValidationRule::NonEmpty,
+        );
+        assert_invalid(
+            attach_key_to_vault(AttachKeyToVaultRequest {
+                key_id: "key-1".to_string(),
+                vault_id: MISSING_VAULT.to_string(),
+            })
+            .await,
+            "vault_id",
+            ValidationRule::ExistingVaultId,
+        );
+        assert_invalid(
+            update_global_key_label(UpdateGlobalKeyLabelRequest {
+                key_id: "key-1".to_string(),
+                new_label: "bad/label".to_string(),
+            })
+            .await,
+            "new_label",
+            ValidationRule::LabelFormat,
+        );
+        assert_invalid(
+            relink_key_file(RelinkKeyFileRequest {
+                key_id: "key-1".to_string(),
+                new_path: MISSING_FILE.to_string(),
+            })
+            .await,
+            "new_path",
+            ValidationRule::ExistingFile,
+        );
+        assert_invalid(
+            add_recipient(AddRecipientRequest {
+                label: "Alice".to_string(),
+                public_key: "not-a-key".to_string(),
+            })
+            .await,
+            "public_key",
+            ValidationRule::Format,
+        );
+        assert_invalid(
+            get_vault_keys(GetVaultKeysRequest {
+                vault_id: MISSING_VAULT.to_string(),
+                include_all: None,
+            })
+            .await,
+            "vault_id",
+            ValidationRule::ExistingVaultId,
+        );
+        assert_invalid(
+            update_key_label(UpdateKeyLabelRequest {
+                vault_id: MISSING_VAULT.to_string(),
+                key_id: "key-1".to_string(),
+                new_label: "Backup".to_string(),
+            })
+            .await,
+            "vault_id",
+            ValidationRule::ExistingVaultId,
+        );
+    }
+
+    #[tokio::test]
+    async fn test_passphrase_and_yubikey_commands_reject_invalid_fields() {
+        assert_invalid(
+            generate_key(GenerateKeyInput {
+                label: String::new(),
+                passphrase: "strong-passphrase-123".to_string(),
+            })
+            .await,
+            "label",
+            ValidationRule::NonEmpty,
+        );
+        assert_invalid(
+            generate_key(GenerateKeyInput {
+                label: "family-backup".to_string(),
+                passphrase: "weak".to_string(),
+            })
+            .await,
+            "passphrase",
+            ValidationRule::PassphraseStrength,
+        );
+        assert_invalid(
+            add_passphrase_key_to_vault(AddPassphraseKeyRequest {
+                vault_id: MISSING_VAULT.to_string(),
+                label: "family-backup".to_string(),
+                passphrase: "strong-passphrase-123".to_string(),
+            })
+            .await,
+            "vault_id",
+            ValidationRule::ExistingVaultId,
+        );
+        assert_invalid(
+            create_recovery_shares(CreateRecoverySharesRequest {
+                vault_id: String::new(),
+                threshold: 2,
+                total: 3,
+            })
+            .await,
+            "vault_id",
+            ValidationRule::ExistingVaultId,
+        );
+        assert_invalid(
+            register_yubikey_for_vault(RegisterYubiKeyForVaultParams {
+                serial: "12345678".to_string(),
+                pin: "123456".to_string(),
+                label: "YubiKey".to_string(),
+                vault_id: MISSING_VAULT.to_string(),
+                fulfills_reservation: None,
+            })
+            .await,
+            "vault_id",
+            ValidationRule::ExistingVaultId,
+        );
+        assert_invalid(
+            reserve_yubikey_slot(ReserveYubiKeySlotParams {
+                vault_id: MISSING_VAULT.to_string(),
+                label: "Backup YubiKey".to_string(),
+            })
+            .await,
+            "vault_id",
+            ValidationRule::ExistingVaultId,
+        );
+    }
+}
+
+#[cfg(test)]
+mod crypto_command_tests {
+    use super::*;
+
+    fn decrypt_input() -> DecryptDataInput {
+        DecryptDataInput {
+            encrypted_file: MISSING_FILE.to_string(),
+            key_id: "key-1".to_string(),
+            passphrase: "passphrase".to_string(),
+            output_dir: None,
+            force_overwrite: None,
+            path_limit_strategy: None,
+            ownership_policy: None,
+            restore_filter: None,
+            io_priority: None,
+            session_ttl_minutes: None,
+            accept_last_attempt: None,
+        }
+    }
+
+    #[test]
+    fn test_decrypt_input_reports_field() {
+        assert_invalid(
+            DecryptDataInput {
+                key_id: String::new(),
+                ..decrypt_input()
+            }
+            .validate(),
+            "key_id",
+            ValidationRule::NonEmptyId,
+        );
+        assert_invalid(
+            DecryptDataInput {
+                session_ttl_minutes: Some(0),
+                ..decrypt_input()
+            }
+            .validate(),
+            "session_ttl_minutes",
+            ValidationRule::InRange,
+        );
+        assert_invalid(
+            decrypt_input().validate(),
+            "encrypted_file",
+            ValidationRule::ExistingFile,
+        );
+    }
+
+    #[test]
+    fn test_encrypt_inputs_report_field() {
+        assert_invalid(
+            EncryptDataInput {
+                key_id: "key-1".to_string(),
+                file_paths: vec![],
+                output_name: None,
+                output_path: None,
+                contact_ids: vec![],
+            }
+            .validate(),
+            "file_paths",
+            ValidationRule::NonEmptyList,
+        );
+        assert_invalid(
+            EncryptFilesMultiInput {
+                vault_id: MISSING_VAULT.to_string(),
+                in_file_paths: vec!["/tmp/file.txt".to_string()],
+                out_encrypted_file_name: None,
+                out_encrypted_file_path: None,
+                locked_file_policy: None,
+                resilient_source: None,
+                comment: None,
+                io_priority: None,
+                armored_output: None,
+                generate_parity: None,
+                contact_ids: vec![],
+                strict: None,
+            }
+            .validate(),
+            "vault_id",
+            ValidationRule::ExistingVaultId,
+        );
+    }
+
+    #[tokio::test]
+    async fn test_crypto_commands_reject_invalid_fields() {
+        assert_invalid(
+            check_decryption_key(CheckDecryptionKeyInput {
+                encrypted_file: MISSING_FILE.to_string(),
+                key_id: "key-1".to_string(),
+            })
+            .await,
+            "encrypted_file",
+            ValidationRule::ExistingFile,
+        );
+        assert_invalid(
+            decrypt_batch(DecryptBatchInput {
+                vault_id: MISSING_VAULT.to_string(),
+                archive_ids: vec!["archive-1".to_string()],
+                output_dir: "/tmp/out".to_string(),
+                key_id: "key-1".to_string(),
+                pin: "123456".to_string(),
+                stop_on_error: None,
+                path_limit_strategy: None,
+            })
+            .await,
+            "vault_id",
+            ValidationRule::ExistingVaultId,
+        );
+        assert_invalid(
+            browse_archive(BrowseArchiveInput {
+                vault_id: MISSING_VAULT.to_string(),
+                archive_id: "archive-1".to_string(),
+                key_id: "key-1".to_string(),
+                passphrase: "passphrase".to_string(),
+            })
+            .await,
+            "vault_id",
+            ValidationRule::ExistingVaultId,
+        );
+        assert_invalid(
+            verify_tool_independence(VerifyToolIndependenceInput {
+                vault_id: MISSING_VAULT.to_string(),
+                archive_id: "archive-1".to_string(),
+                key_id: "key-1".to_string(),
+                passphrase: "passphrase".to_string(),
+            })
+            .await,
+            "vault_id",
+            ValidationRule::ExistingVaultId,
+        );
+        assert_invalid(
+            stop_browsing(StopBrowsingInput {
+                session_id: String::new(),
+            })
+            .await,
+            "session_id",
+            ValidationRule::NonEmptyId,
+        );
+        assert_invalid(
+            assess_salvage(AssessSalvageInput {
+                encrypted_file: MISSING_FILE.to_string(),
+                key_id: "key-1".to_string(),
+                passphrase: "passphrase".to_string(),
+            })
+            .await,
+            "encrypted_file",
+            ValidationRule::ExistingFile,
+        );
+        assert_invalid(
+            salvage_decrypt(SalvageDecryptInput {
+                encrypted_file: String::new(),
+                key_id: "key-1".to_string(),
+                passphrase: "passphrase".to_string(),
+                output_dir: "/tmp/out".to_string(),
+                accept_partial: true,
+            })
+            .await,
+            "encrypted_file",
+            ValidationRule::NonEmpty,
+        );
+        assert_invalid(
+            regenerate_external_manifest(RegenerateExternalManifestInput {
+                encrypted_file: MISSING_FILE.to_string(),
+                key_id: String::new(),
+                passphrase: "passphrase".to_string(),
+            })
+            .await,
+            "key_id",
+            ValidationRule::NonEmptyId,
+        );
+        assert_invalid(
+            decrypt_with_recovery_shares(DecryptWithRecoverySharesInput {
+                vault_id: String::new(),
+                shares: vec!["share".to_string()],
+                encrypted_file: MISSING_FILE.to_string(),
+                output_dir: "/tmp/out".to_string(),
+            })
+            .await,
+            "vault_id",
+            ValidationRule::NonEmptyId,
+        );
+        assert_invalid(
+            verify_manifest(VerifyManifestInput {
+                manifest_path: String::new(),
+                extracted_files_dir: "/tmp".to_string(),
+                strictness: Default::default(),
+            })
+            .await,
+            "manifest_path",
+            ValidationRule::NonEmpty,
+        );
+        assert_invalid(
+            analyze_encrypted_vault(AnalyzeEncryptedVaultRequest {
+                encrypted_file_path: MISSING_FILE.to_string(),
+                key_id: None,
+                passphrase: None,
+                preview: false,
+            })
+            .await,
+            "encrypted_file_path",
+            ValidationRule::ExistingFile,
+        );
+    }
+
+    #[tokio::test]
+    async fn test_progress_and_cleanup_commands_reject_invalid_fields() {
+        assert_invalid(
+            get_progress(GetProgressInput {
+                operation_id: "x".repeat(101),
+            })
+            .await,
+            "operation_id",
+            ValidationRule::MaxLength,
+        );
+        assert_invalid(
+            extend_cleanup_session(ExtendCleanupSessionInput {
+                session_id: "session-1".to_string(),
+                minutes: 0,
+            })
+            .await,
+            "minutes",
+            ValidationRule::InRange,
+        );
+        assert_invalid(
+            cancel_cleanup_session(CancelCleanupSessionInput {
+                session_id: String::new(),
+            })
+            .await,
+            "session_id",
+            ValidationRule::NonEmptyId,
+        );
+    }
+}
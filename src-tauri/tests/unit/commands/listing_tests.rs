@@ -0,0 +1,116 @@
+//! Sorting and pagination of list commands
+//!
+//! Walks every page of a synthetic 1,000-entry archive index and checks each
+//! archive comes back exactly once, in the same order on every walk, even
+//! when the sort field has the same value for many archives.
+
+use barqly_vault_lib::commands::listing::{
+    PageRequest, SortDirection, SortSpec, sort_and_paginate,
+};
+use barqly_vault_lib::commands::vault::ArchiveSortField;
+use barqly_vault_lib::services::vault::domain::models::{ArchiveIndexEntry, ArchiveListing};
+use chrono::{Duration, TimeZone, Utc};
+use std::collections::HashSet;
+
+const ARCHIVE_COUNT: usize = 1_000;
+
+/// Archives in scrambled order; ten share each revision and timestamp
+fn synthetic_index() -> Vec<ArchiveListing> {
+    let base = Utc.with_ymd_and_hms(2026, 1, 1, 0, 0, 0).unwrap();
+    (0..ARCHIVE_COUNT)
+        .map(|i| (i * 7_919) % ARCHIVE_COUNT)
+        .map(|n| ArchiveListing {
+            entry: ArchiveIndexEntry {
+                archive_id: format!("archive-{n:04}"),
+                vault_id: "vault-1".to_string(),
+                archive_name: "Family-Documents.age".to_string(),
+                encryption_revision: (n / 10) as u32,
+                created_at: base + Duration::minutes((n / 10) as i64),
+                file_count: n % 3,
+                comment: None,
+                comment_updated_at: None,
+                immutable: false,
+                parity: None,
+                migrated_from: None,
+                prune_marked: false,
+            },
+            current: n == ARCHIVE_COUNT - 1,
+            os_protected: false,
+            retention: None,
+        })
+        .collect()
+}
+
+fn walk_pages(sort: Option<SortSpec<ArchiveSortField>>, limit: usize) -> Vec<String> {
+    let mut ids = Vec::new();
+    let mut offset = 0;
+    loop {
+        let page = sort_and_paginate(
+            synthetic_index(),
+            sort,
+            ArchiveSortField::CreatedAt,
+            Some(PageRequest {
+                offset,
+                limit: Some(limit),
+            }),
+        );
+        assert_eq!(page.total_count, ARCHIVE_COUNT);
+        if page.items.is_empty() {
+            return ids;
+        }
+        offset += page.items.len();
+        ids.extend(
+            page.items
+                .into_iter()
+                .map(|listing| listing.entry.archive_id),
+        );
+    }
+}
+
+fn assert_complete(ids: &[String]) {
+    assert_eq!(ids.len(), ARCHIVE_COUNT);
+    let unique: HashSet<_> = ids.iter().collect();
+    assert_eq!(unique.len(), ARCHIVE_COUNT, "an archive was listed twice");
+}
+
+#[test]
+fn every_page_walk_is_complete_and_stable() {
+    for field in [
+        ArchiveSortField::CreatedAt,
+        ArchiveSortField::ArchiveName,
+        ArchiveSortField::EncryptionRevision,
+        ArchiveSortField::FileCount,
+    ] {
+        for direction in [SortDirection::Asc, SortDirection::Desc] {
+            let sort = Some(SortSpec { field, direction });
+            let first = walk_pages(sort, 37);
+            assert_complete(&first);
+
+            // Page size doesn't change the order
+            assert_eq!(first, walk_pages(sort, 100));
+            assert_eq!(first, walk_pages(sort, 1));
+        }
+    }
+}
+
+#[test]
+fn equal_sort_values_are_ordered_by_id() {
+    // Every archive has the same name, so the id alone decides the order
+    let sort = Some(SortSpec {
+        field: ArchiveSortField::ArchiveName,
+        direction: SortDirection::Asc,
+    });
+    let ids = walk_pages(sort, 64);
+
+    let mut expected = ids.clone();
+    expected.sort();
+    assert_eq!(ids, expected);
+}
+
+#[test]
+fn default_sort_is_oldest_first() {
+    let ids = walk_pages(None, 250);
+    assert_complete(&ids);
+    assert_eq!(ids.first().map(String::as_str), Some("archive-0000"));
+    assert_eq!(ids.last().map(String::as_str), Some("archive-0999"));
+}
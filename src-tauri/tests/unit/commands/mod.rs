@@ -7,6 +7,8 @@
 //! - Error code mapping and formatting
 //! - Progress update structures
 
+pub mod input_rules_tests;
+pub mod listing_tests;
 pub mod output_path_tests;
 pub mod types_tests;
 pub mod validation_tests;
@@ -35,6 +35,7 @@ fn test_encrypt_input_with_valid_output_path() {
             file_paths: vec![test_file.to_string_lossy().to_string()],
             output_name: Some("output.age".to_string()),
             output_path: Some(dir_path.to_string_lossy().to_string()),
+            contact_ids: vec![],
         };
 
         // Should succeed for existing, writable directory
@@ -58,6 +59,7 @@ fn test_encrypt_input_with_non_existent_output_path() {
             file_paths: vec![test_file.to_string_lossy().to_string()],
             output_name: Some("output.age".to_string()),
             output_path: Some(non_existent_path.to_string_lossy().to_string()),
+            contact_ids: vec![],
         };
 
         // Input validation should pass - directory will be created during execution
@@ -81,6 +83,7 @@ fn test_encrypt_input_with_file_as_output_path() {
             file_paths: vec![file_path.to_string_lossy().to_string()],
             output_name: Some("output.age".to_string()),
             output_path: Some(file_path.to_string_lossy().to_string()),
+            contact_ids: vec![],
         };
 
         // Input validation may pass, actual error would occur at runtime
@@ -113,6 +116,7 @@ fn test_encrypt_input_with_read_only_output_path() {
             file_paths: vec![test_file.to_string_lossy().to_string()],
             output_name: Some("output.age".to_string()),
             output_path: Some(readonly_dir.to_string_lossy().to_string()),
+            contact_ids: vec![],
         };
 
         // Input validation may pass, actual permission error would occur at runtime
@@ -134,6 +138,7 @@ fn test_encrypt_input_with_empty_output_path() {
             file_paths: vec![test_file.to_string_lossy().to_string()],
             output_name: Some("output.age".to_string()),
             output_path: Some("".to_string()), // Empty path
+            contact_ids: vec![],
         };
 
         // Empty output_path might be rejected or treated as None
@@ -155,6 +160,7 @@ fn test_encrypt_input_with_special_chars_in_path() {
             file_paths: vec![test_file.to_string_lossy().to_string()],
             output_name: Some("output.age".to_string()),
             output_path: Some(special_dir.to_string_lossy().to_string()),
+            contact_ids: vec![],
         };
 
         let result = encrypt_input.validate();
@@ -179,6 +185,7 @@ fn test_encrypt_input_with_nested_path() {
             file_paths: vec![test_file.to_string_lossy().to_string()],
             output_name: Some("output.age".to_string()),
             output_path: Some(nested_path.to_string_lossy().to_string()),
+            contact_ids: vec![],
         };
 
         let result = encrypt_input.validate();
@@ -206,6 +213,7 @@ fn test_encrypt_input_with_symlink_path() {
                 file_paths: vec![test_file.to_string_lossy().to_string()],
                 output_name: Some("output.age".to_string()),
                 output_path: Some(symlink_path.to_string_lossy().to_string()),
+                contact_ids: vec![],
             };
 
             let result = encrypt_input.validate();
@@ -238,6 +246,7 @@ fn test_path_joining_bug_regression() {
             file_paths: vec![test_file.to_string_lossy().to_string()],
             output_name: Some(relative_name.to_string()),
             output_path: None, // Will use current dir and join with output_name
+            contact_ids: vec![],
         };
 
         // Get current directory for expected result
@@ -274,6 +283,7 @@ fn test_absolute_path_handling() {
             file_paths: vec![test_file.to_string_lossy().to_string()],
             output_name: Some("test_output".to_string()),
             output_path: Some(absolute_path.to_string_lossy().to_string()),
+            contact_ids: vec![],
         };
 
         assert!(
@@ -313,6 +323,7 @@ fn test_various_path_combinations() {
                 file_paths: vec![test_file.to_string_lossy().to_string()],
                 output_name,
                 output_path,
+                contact_ids: vec![],
             };
 
             let result = encrypt_input.validate();
@@ -342,6 +353,7 @@ fn test_encrypt_data_input_with_output_path() {
             file_paths: vec![test_file.to_string_lossy().to_string()],
             output_name: Some("custom_name".to_string()),
             output_path: Some(output_path.clone()),
+            contact_ids: vec![],
         };
 
         let result = input.validate();
@@ -367,6 +379,7 @@ fn test_encrypt_data_input_without_output_path() {
             file_paths: vec![test_file.to_string_lossy().to_string()],
             output_name: Some("output".to_string()),
             output_path: None, // No output path specified
+            contact_ids: vec![],
         };
 
         let result = input.validate();
@@ -385,6 +398,7 @@ fn test_encrypt_data_input_validates_required_fields() {
             file_paths: vec!["test.txt".to_string()],
             output_name: None,
             output_path: None,
+            contact_ids: vec![],
         };
 
         let result = input.validate();
@@ -399,6 +413,7 @@ fn test_encrypt_data_input_validates_required_fields() {
             file_paths: vec![],
             output_name: None,
             output_path: None,
+            contact_ids: vec![],
         };
 
         let result = input.validate();
@@ -425,6 +440,7 @@ fn test_encrypt_data_input_with_spaces_in_paths() {
             file_paths: vec![file_with_spaces.to_string_lossy().to_string()],
             output_name: Some("output with spaces".to_string()),
             output_path: Some(dir_with_spaces.to_string_lossy().to_string()),
+            contact_ids: vec![],
         };
 
         let result = input.validate();
@@ -451,6 +467,7 @@ fn test_encrypt_data_input_with_unicode_paths() {
             file_paths: vec![unicode_file.to_string_lossy().to_string()],
             output_name: Some("加密_🔐".to_string()),
             output_path: Some(unicode_dir.to_string_lossy().to_string()),
+            contact_ids: vec![],
         };
 
         let result = input.validate();
@@ -8,7 +8,8 @@
 //! - Input validation traits
 
 use barqly_vault_lib::commands::types::{
-    CommandError, CommandResponse, ErrorCode, ProgressDetails, ProgressUpdate, ValidateInput,
+    ByteSize, CommandError, CommandResponse, ErrorCode, IoPriority, ProgressDetails,
+    ProgressUpdate, ValidateInput,
 };
 use serde_json;
 
@@ -121,6 +122,8 @@ fn test_progress_update_creation() {
             details: None,
             timestamp: chrono::Utc::now(),
             estimated_time_remaining: None,
+            io_priority: IoPriority::Normal,
+            trace_id: None,
         };
 
         assert_eq!(
@@ -138,8 +141,8 @@ fn test_progress_update_with_file_details() {
             current_file: "file1.txt".to_string(),
             total_files: 5,
             current_file_progress: 0.3,
-            current_file_size: 1024,
-            total_size: 5120,
+            current_file_size: ByteSize(1024),
+            total_size: ByteSize(5120),
         };
 
         let progress = ProgressUpdate {
@@ -149,6 +152,8 @@ fn test_progress_update_with_file_details() {
             details: Some(details),
             timestamp: chrono::Utc::now(),
             estimated_time_remaining: None,
+            io_priority: IoPriority::Normal,
+            trace_id: None,
         };
 
         assert_eq!(
@@ -176,8 +181,8 @@ fn test_progress_update_with_file_details() {
     #[test]
     fn test_progress_update_with_encryption_details() {
         let details = ProgressDetails::Encryption {
-            bytes_processed: 1024,
-            total_bytes: 2048,
+            bytes_processed: ByteSize(1024),
+            total_bytes: ByteSize(2048),
             encryption_rate: Some(512.0),
         };
 
@@ -188,6 +193,8 @@ fn test_progress_update_with_encryption_details() {
             details: Some(details),
             timestamp: chrono::Utc::now(),
             estimated_time_remaining: None,
+            io_priority: IoPriority::Normal,
+            trace_id: None,
         };
 
         if let Some(ProgressDetails::Encryption {
@@ -196,8 +203,8 @@ fn test_progress_update_with_encryption_details() {
             encryption_rate: _,
         }) = progress.details
         {
-            assert_eq!(bytes_processed, 1024);
-            assert_eq!(total_bytes, 2048);
+            assert_eq!(bytes_processed, ByteSize(1024));
+            assert_eq!(total_bytes, ByteSize(2048));
         } else {
             panic!("Should have encryption details");
         }
@@ -212,6 +219,8 @@ fn test_progress_update_serialization() {
             details: None,
             timestamp: chrono::Utc::now(),
             estimated_time_remaining: None,
+            io_priority: IoPriority::Normal,
+            trace_id: None,
         };
 
         let serialized = serde_json::to_string(&progress).expect("Should serialize");
@@ -238,8 +247,8 @@ fn test_progress_update_with_details_serialization() {
             current_file: "test.txt".to_string(),
             total_files: 10,
             current_file_progress: 0.5,
-            current_file_size: 2048,
-            total_size: 10240,
+            current_file_size: ByteSize(2048),
+            total_size: ByteSize(10240),
         };
 
         let progress = ProgressUpdate {
@@ -249,6 +258,8 @@ fn test_progress_update_with_details_serialization() {
             details: Some(details),
             timestamp: chrono::Utc::now(),
             estimated_time_remaining: None,
+            io_priority: IoPriority::Normal,
+            trace_id: None,
         };
 
         let serialized = serde_json::to_string(&progress).expect("Should serialize");
@@ -402,6 +413,8 @@ fn test_large_progress_values() {
             details: None,
             timestamp: chrono::Utc::now(),
             estimated_time_remaining: None,
+            io_priority: IoPriority::Normal,
+            trace_id: None,
         };
 
         let serialized = serde_json::to_string(&progress).expect("Should serialize");
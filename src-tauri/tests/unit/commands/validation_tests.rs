@@ -125,6 +125,7 @@ fn test_encrypt_data_input_validation_success() {
             ],
             output_name: Some("encrypted_output".to_string()),
             output_path: None,
+            contact_ids: vec![],
         };
 
         let result = input.validate();
@@ -138,6 +139,7 @@ fn test_encrypt_data_input_empty_key_id() {
             file_paths: vec!["/path/to/file.txt".to_string()],
             output_name: None,
             output_path: None,
+            contact_ids: vec![],
         };
 
         let result = input.validate();
@@ -158,6 +160,7 @@ fn test_encrypt_data_input_empty_file_paths() {
             file_paths: vec![],
             output_name: None,
             output_path: None,
+            contact_ids: vec![],
         };
 
         let result = input.validate();
@@ -199,6 +202,12 @@ fn test_decrypt_data_input_validation_success() {
             passphrase: "strong-passphrase-123".to_string(),
             output_dir: Some(output_dir.to_string_lossy().to_string()),
             force_overwrite: None,
+            path_limit_strategy: None,
+            ownership_policy: None,
+            restore_filter: None,
+            io_priority: None,
+            session_ttl_minutes: None,
+            accept_last_attempt: None,
         };
 
         let result = input.validate();
@@ -216,6 +225,12 @@ fn test_decrypt_data_input_empty_encrypted_file() {
             passphrase: "strong-passphrase-123".to_string(),
             output_dir: Some("/path/to/output".to_string()),
             force_overwrite: None,
+            path_limit_strategy: None,
+            ownership_policy: None,
+            restore_filter: None,
+            io_priority: None,
+            session_ttl_minutes: None,
+            accept_last_attempt: None,
         };
 
         let result = input.validate();
@@ -240,6 +255,12 @@ fn test_decrypt_data_input_empty_key_id() {
             passphrase: "strong-passphrase-123".to_string(),
             output_dir: Some("/path/to/output".to_string()),
             force_overwrite: None,
+            path_limit_strategy: None,
+            ownership_policy: None,
+            restore_filter: None,
+            io_priority: None,
+            session_ttl_minutes: None,
+            accept_last_attempt: None,
         };
 
         let result = input.validate();
@@ -261,6 +282,12 @@ fn test_decrypt_data_input_empty_passphrase() {
             passphrase: "".to_string(),
             output_dir: Some("/path/to/output".to_string()),
             force_overwrite: None,
+            path_limit_strategy: None,
+            ownership_policy: None,
+            restore_filter: None,
+            io_priority: None,
+            session_ttl_minutes: None,
+            accept_last_attempt: None,
         };
 
         let result = input.validate();
@@ -282,6 +309,12 @@ fn test_decrypt_data_input_empty_output_dir() {
             passphrase: "strong-passphrase-123".to_string(),
             output_dir: Some("".to_string()),
             force_overwrite: None,
+            path_limit_strategy: None,
+            ownership_policy: None,
+            restore_filter: None,
+            io_priority: None,
+            session_ttl_minutes: None,
+            accept_last_attempt: None,
         };
 
         let result = input.validate();
@@ -600,6 +633,7 @@ fn test_encrypt_files_input_validation_success() {
             ],
             output_name: Some("encrypted_output.age".to_string()),
             output_path: None,
+            contact_ids: vec![],
         };
 
         let result = input.validate();
@@ -616,6 +650,7 @@ fn test_encrypt_files_input_single_folder() {
             file_paths: vec!["/path/to/folder".to_string()],
             output_name: None,
             output_path: None,
+            contact_ids: vec![],
         };
 
         let result = input.validate();
@@ -629,6 +664,7 @@ fn test_encrypt_files_input_empty_key_id() {
             file_paths: vec!["/path/to/file.txt".to_string()],
             output_name: None,
             output_path: None,
+            contact_ids: vec![],
         };
 
         let result = input.validate();
@@ -649,6 +685,7 @@ fn test_encrypt_files_input_empty_file_paths() {
             file_paths: vec![],
             output_name: None,
             output_path: None,
+            contact_ids: vec![],
         };
 
         let result = input.validate();
@@ -669,6 +706,7 @@ fn test_encrypt_files_input_with_output_name() {
             file_paths: vec!["/path/to/file.txt".to_string()],
             output_name: Some("custom_output.age".to_string()),
             output_path: None,
+            contact_ids: vec![],
         };
 
         let result = input.validate();
@@ -830,6 +868,7 @@ fn test_encrypt_files_input_very_long_paths() {
             file_paths: vec![long_path],
             output_name: None,
             output_path: None,
+            contact_ids: vec![],
         };
 
         let result = input.validate();
@@ -847,6 +886,7 @@ fn test_encrypt_files_input_many_files() {
             file_paths: many_files,
             output_name: None,
             output_path: None,
+            contact_ids: vec![],
         };
 
         let result = input.validate();
@@ -905,6 +945,7 @@ fn should_allow_path_traversal_in_input_validation_but_catch_in_file_ops() {
                 file_paths: vec![malicious_path.to_string()],
                 output_name: None,
                 output_path: None,
+                contact_ids: vec![],
             };
 
             let result = input.validate();
@@ -924,6 +965,7 @@ fn should_reject_symlink_attempts_in_encrypt_files() {
             file_paths: vec!["/tmp/symlink_to_sensitive".to_string()],
             output_name: None,
             output_path: None,
+            contact_ids: vec![],
         };
 
         let result = input.validate();
@@ -950,6 +992,7 @@ fn should_reject_absolute_paths_outside_allowed_directories() {
                 file_paths: vec![restricted_path.to_string()],
                 output_name: None,
                 output_path: None,
+                contact_ids: vec![],
             };
 
             let result = input.validate();
@@ -977,6 +1020,7 @@ fn should_handle_very_large_file_lists() {
             file_paths: large_file_list,
             output_name: None,
             output_path: None,
+            contact_ids: vec![],
         };
 
         let result = input.validate();
@@ -996,6 +1040,7 @@ fn should_handle_very_long_file_paths() {
             file_paths: vec![long_path],
             output_name: None,
             output_path: None,
+            contact_ids: vec![],
         };
 
         let result = input.validate();
@@ -1015,6 +1060,7 @@ fn should_prevent_concurrent_encryption_operations() {
             file_paths: vec!["/path/to/file1.txt".to_string()],
             output_name: None,
             output_path: None,
+            contact_ids: vec![],
         };
 
         let input2 = EncryptDataInput {
@@ -1022,6 +1068,7 @@ fn should_prevent_concurrent_encryption_operations() {
             file_paths: vec!["/path/to/file2.txt".to_string()],
             output_name: None,
             output_path: None,
+            contact_ids: vec![],
         };
 
         // Both inputs should validate successfully
@@ -1043,6 +1090,7 @@ fn should_validate_archive_size_limits() {
             file_paths: vec!["/path/to/large_file.bin".to_string()],
             output_name: None,
             output_path: None,
+            contact_ids: vec![],
         };
 
         let result = input.validate();
@@ -1064,6 +1112,7 @@ fn should_handle_file_system_permission_errors() {
             file_paths: vec!["/root/protected_file.txt".to_string()],
             output_name: None,
             output_path: None,
+            contact_ids: vec![],
         };
 
         let result = input.validate();
@@ -1081,6 +1130,7 @@ fn should_handle_network_file_system_failures() {
             file_paths: vec!["/mnt/nfs/unavailable/file.txt".to_string()],
             output_name: None,
             output_path: None,
+            contact_ids: vec![],
         };
 
         let result = input.validate();
@@ -1104,18 +1154,21 @@ fn should_handle_boundary_values_in_validation() {
                 file_paths: vec!["/path/to/file.txt".to_string()],
                 output_name: None,
                 output_path: None,
+                contact_ids: vec![],
             },
             EncryptDataInput {
                 key_id: "a".to_string(), // Single character
                 file_paths: vec!["/path/to/file.txt".to_string()],
                 output_name: None,
                 output_path: None,
+                contact_ids: vec![],
             },
             EncryptDataInput {
                 key_id: "test-key-id".to_string(),
                 file_paths: vec![], // Empty file list
                 output_name: None,
                 output_path: None,
+                contact_ids: vec![],
             },
         ];
 
@@ -1153,6 +1206,7 @@ fn should_handle_unicode_and_special_characters() {
                 file_paths: vec![unicode_path.to_string()],
                 output_name: None,
                 output_path: None,
+                contact_ids: vec![],
             };
 
             let result = input.validate();
@@ -1169,6 +1223,8 @@ mod task_3_4_command_tests {
     use super::*;
     use barqly_vault_lib::commands::crypto::DecryptDataInput;
     use barqly_vault_lib::commands::crypto::{VerifyManifestInput, VerifyManifestResponse};
+    use barqly_vault_lib::services::file::infrastructure::file_operations::VerificationStrictness;
+    use barqly_vault_lib::types::ByteSize;
 
     #[test]
     fn test_decrypt_data_input_empty_encrypted_file() {
@@ -1178,6 +1234,12 @@ fn test_decrypt_data_input_empty_encrypted_file() {
             passphrase: "test-passphrase".to_string(),
             output_dir: Some("/tmp/output".to_string()),
             force_overwrite: None,
+            path_limit_strategy: None,
+            ownership_policy: None,
+            restore_filter: None,
+            io_priority: None,
+            session_ttl_minutes: None,
+            accept_last_attempt: None,
         };
         assert!(input.validate().is_err());
     }
@@ -1190,6 +1252,12 @@ fn test_decrypt_data_input_empty_key_id() {
             passphrase: "test-passphrase".to_string(),
             output_dir: Some("/tmp/output".to_string()),
             force_overwrite: None,
+            path_limit_strategy: None,
+            ownership_policy: None,
+            restore_filter: None,
+            io_priority: None,
+            session_ttl_minutes: None,
+            accept_last_attempt: None,
         };
         assert!(input.validate().is_err());
     }
@@ -1202,6 +1270,12 @@ fn test_decrypt_data_input_empty_passphrase() {
             passphrase: "".to_string(),
             output_dir: Some("/tmp/output".to_string()),
             force_overwrite: None,
+            path_limit_strategy: None,
+            ownership_policy: None,
+            restore_filter: None,
+            io_priority: None,
+            session_ttl_minutes: None,
+            accept_last_attempt: None,
         };
         assert!(input.validate().is_err());
     }
@@ -1214,6 +1288,12 @@ fn test_decrypt_data_input_empty_output_dir() {
             passphrase: "test-passphrase".to_string(),
             output_dir: Some("".to_string()),
             force_overwrite: None,
+            path_limit_strategy: None,
+            ownership_policy: None,
+            restore_filter: None,
+            io_priority: None,
+            session_ttl_minutes: None,
+            accept_last_attempt: None,
         };
         assert!(input.validate().is_err());
     }
@@ -1246,6 +1326,12 @@ fn test_decrypt_data_input_validation_success() {
             passphrase: "test-passphrase".to_string(),
             output_dir: Some(output_dir.to_string_lossy().to_string()),
             force_overwrite: None,
+            path_limit_strategy: None,
+            ownership_policy: None,
+            restore_filter: None,
+            io_priority: None,
+            session_ttl_minutes: None,
+            accept_last_attempt: None,
         };
         assert!(input.validate().is_ok());
 
@@ -1281,6 +1367,12 @@ fn test_decrypt_data_input_unicode_paths() {
             passphrase: "test-passphrase".to_string(),
             output_dir: Some(output_dir.to_string_lossy().to_string()),
             force_overwrite: None,
+            path_limit_strategy: None,
+            ownership_policy: None,
+            restore_filter: None,
+            io_priority: None,
+            session_ttl_minutes: None,
+            accept_last_attempt: None,
         };
         assert!(input.validate().is_ok());
 
@@ -1293,6 +1385,7 @@ fn test_verify_manifest_input_empty_manifest_path() {
         let input = VerifyManifestInput {
             manifest_path: "".to_string(),
             extracted_files_dir: "/tmp/extracted".to_string(),
+            strictness: VerificationStrictness::default(),
         };
         assert!(input.validate().is_err());
     }
@@ -1302,6 +1395,7 @@ fn test_verify_manifest_input_empty_extracted_files_dir() {
         let input = VerifyManifestInput {
             manifest_path: "/path/to/manifest.json".to_string(),
             extracted_files_dir: "".to_string(),
+            strictness: VerificationStrictness::default(),
         };
         assert!(input.validate().is_err());
     }
@@ -1311,6 +1405,7 @@ fn test_verify_manifest_input_validation_success() {
         let input = VerifyManifestInput {
             manifest_path: "/path/to/manifest.json".to_string(),
             extracted_files_dir: "/tmp/extracted".to_string(),
+            strictness: VerificationStrictness::default(),
         };
         // This will fail validation because the files don't exist, but the format is correct
         // We can't easily test the file existence check in unit tests
@@ -1324,6 +1419,7 @@ fn test_verify_manifest_input_unicode_paths() {
         let input = VerifyManifestInput {
             manifest_path: "/path/with/unicode/manifest文件.json".to_string(),
             extracted_files_dir: "/tmp/extracted/输出".to_string(),
+            strictness: VerificationStrictness::default(),
         };
         // This will fail validation because the files don't exist, but the format is correct
         let result = input.validate();
@@ -1337,11 +1433,14 @@ fn test_verify_manifest_response_creation() {
             is_valid: true,
             message: "Manifest verification successful".to_string(),
             file_count: 5,
-            total_size: 1024,
+            total_size: ByteSize(1024),
+            signature: None,
+            strictness: VerificationStrictness::ListedOnly,
+            unexpected: None,
         };
         assert!(response.is_valid);
         assert_eq!(response.file_count, 5);
-        assert_eq!(response.total_size, 1024);
+        assert_eq!(response.total_size, ByteSize(1024));
         assert!(response.message.contains("successful"));
     }
 
@@ -1351,11 +1450,14 @@ fn test_verify_manifest_response_failure() {
             is_valid: false,
             message: "Manifest verification failed: hash mismatch".to_string(),
             file_count: 3,
-            total_size: 512,
+            total_size: ByteSize(512),
+            signature: None,
+            strictness: VerificationStrictness::ExactMatch,
+            unexpected: None,
         };
         assert!(!response.is_valid);
         assert_eq!(response.file_count, 3);
-        assert_eq!(response.total_size, 512);
+        assert_eq!(response.total_size, ByteSize(512));
         assert!(response.message.contains("failed"));
     }
 }
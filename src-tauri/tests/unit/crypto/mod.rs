@@ -8,3 +8,4 @@
 
 pub mod age_ops_tests;
 pub mod key_mgmt_tests;
+pub mod tool_independence_tests;
@@ -0,0 +1,160 @@
+//! Tests for the standard tools (age + tar) independence check
+//!
+//! Each fixture is a real age-encrypted tar.gz; the generated recovery
+//! transcript is compared against a golden file in `golden/`.
+
+use age::x25519::Identity;
+use barqly_vault_lib::services::crypto::application::services::{
+    ArchiveEncoding, PayloadCompression, ProprietaryFraming, ToolIndependenceService,
+};
+use barqly_vault_lib::services::crypto::infrastructure::{ArchivePreamble, armor_archive};
+use flate2::Compression;
+use flate2::write::GzEncoder;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+const ARCHIVE_NAME: &str = "Family-Photos.age";
+
+fn tar_gz() -> Vec<u8> {
+    let mut builder = tar::Builder::new(GzEncoder::new(Vec::new(), Compression::default()));
+    for (path, content) in [
+        ("photos/beach.jpg", b"beach".as_slice()),
+        ("photos/hike.jpg", b"hike".as_slice()),
+        ("notes.txt", b"notes".as_slice()),
+    ] {
+        let mut header = tar::Header::new_ustar();
+        header.set_size(content.len() as u64);
+        header.set_mode(0o644);
+        header.set_cksum();
+        builder.append_data(&mut header, path, content).unwrap();
+    }
+    builder.into_inner().unwrap().finish().unwrap()
+}
+
+fn encrypt(identity: &Identity, plaintext: &[u8]) -> Vec<u8> {
+    let recipient = identity.to_public();
+    let encryptor =
+        age::Encryptor::with_recipients(std::iter::once(&recipient as &dyn age::Recipient))
+            .unwrap();
+    let mut encrypted = Vec::new();
+    let mut writer = encryptor.wrap_output(&mut encrypted).unwrap();
+    writer.write_all(plaintext).unwrap();
+    writer.finish().unwrap();
+    encrypted
+}
+
+fn archive_path(dir: &TempDir) -> PathBuf {
+    dir.path().join(ARCHIVE_NAME)
+}
+
+fn assert_transcript(transcript: &str, golden: &str, golden_name: &str) {
+    assert_eq!(
+        transcript, golden,
+        "transcript differs from golden/{golden_name}"
+    );
+}
+
+fn split_into_volumes(archive: &Path, encrypted: &[u8], volumes: usize) {
+    let size = encrypted.len().div_ceil(volumes);
+    for (index, chunk) in encrypted.chunks(size).enumerate() {
+        let mut name = archive.as_os_str().to_os_string();
+        name.push(format!(".{:03}", index + 1));
+        fs::write(PathBuf::from(name), chunk).unwrap();
+    }
+}
+
+#[test]
+fn should_verify_plain_archive_with_standard_tools() {
+    let temp = TempDir::new().unwrap();
+    let identity = Identity::generate();
+    let archive = archive_path(&temp);
+    fs::write(&archive, encrypt(&identity, &tar_gz())).unwrap();
+
+    let report = ToolIndependenceService::new()
+        .check(&archive, &identity)
+        .unwrap();
+
+    assert!(report.is_independent());
+    assert!(!report.requires_documented_formats());
+    assert_eq!(report.encoding, ArchiveEncoding::Binary);
+    assert_eq!(report.compression, Some(PayloadCompression::Gzip));
+    assert_eq!(report.tar_entry_count, 3);
+    assert_transcript(
+        &report.transcript,
+        include_str!("golden/plain_transcript.txt"),
+        "plain_transcript.txt",
+    );
+}
+
+#[test]
+fn should_add_reassembly_steps_for_chunked_archive() {
+    let temp = TempDir::new().unwrap();
+    let identity = Identity::generate();
+    let archive = archive_path(&temp);
+    split_into_volumes(&archive, &encrypt(&identity, &tar_gz()), 2);
+    fs::write(temp.path().join(format!("{ARCHIVE_NAME}.par")), b"parity").unwrap();
+
+    let report = ToolIndependenceService::new()
+        .check(&archive, &identity)
+        .unwrap();
+
+    assert!(report.is_independent());
+    assert!(report.requires_documented_formats());
+    assert_eq!(
+        report.framing,
+        vec![
+            ProprietaryFraming::SplitVolumes {
+                volumes: vec![format!("{ARCHIVE_NAME}.001"), format!("{ARCHIVE_NAME}.002")],
+            },
+            ProprietaryFraming::ParitySidecar {
+                file_name: format!("{ARCHIVE_NAME}.par"),
+            },
+        ]
+    );
+    assert_transcript(
+        &report.transcript,
+        include_str!("golden/chunked_transcript.txt"),
+        "chunked_transcript.txt",
+    );
+}
+
+#[test]
+fn should_strip_preamble_for_armored_archive() {
+    let temp = TempDir::new().unwrap();
+    let identity = Identity::generate();
+    let archive = archive_path(&temp);
+    let preamble = ArchivePreamble::new(chrono::Utc::now(), "barqly.vault.manifest/3", None);
+    let armored = armor_archive(&encrypt(&identity, &tar_gz()), &preamble).unwrap();
+    fs::write(&archive, armored).unwrap();
+
+    let report = ToolIndependenceService::new()
+        .check(&archive, &identity)
+        .unwrap();
+
+    assert!(report.is_independent());
+    assert!(report.has_preamble);
+    assert_eq!(report.encoding, ArchiveEncoding::Armored);
+    assert_transcript(
+        &report.transcript,
+        include_str!("golden/armored_transcript.txt"),
+        "armored_transcript.txt",
+    );
+}
+
+#[test]
+fn should_report_failure_for_wrong_identity() {
+    let temp = TempDir::new().unwrap();
+    let archive = archive_path(&temp);
+    fs::write(&archive, encrypt(&Identity::generate(), &tar_gz())).unwrap();
+
+    let report = ToolIndependenceService::new()
+        .check(&archive, &Identity::generate())
+        .unwrap();
+
+    assert!(!report.is_independent());
+    assert!(!report.decrypts_with_identity);
+    assert!(report.failure.is_some());
+    assert!(report.transcript.contains("WARNING: the check failed"));
+}
@@ -1,7 +1,7 @@
 //! Unit tests for archive operations
 
 use barqly_vault_lib::services::file::infrastructure::file_operations::archive_operations::{
-    create_archive, create_archive_with_progress, extract_archive,
+    create_archive, create_archive_with_progress, extract_archive, extract_archive_with_report,
 };
 use barqly_vault_lib::services::file::infrastructure::file_operations::{
     FileOpsConfig, FileOpsError, FileSelection,
@@ -422,3 +422,54 @@ fn test_extract_archive_validates_symlink_targets() {
         );
     }
 }
+
+#[test]
+fn test_nested_empty_directories_round_trip() {
+    let temp_dir = tempdir().unwrap();
+    let folder = temp_dir.path().join("project");
+    fs::create_dir_all(folder.join("empty/deeper/deepest")).unwrap();
+    fs::create_dir_all(folder.join("src")).unwrap();
+    create_test_file(&folder.join("src"), "main.rs", "fn main() {}");
+
+    let archive_path = temp_dir.path().join("project.tar.gz");
+    let config = FileOpsConfig::default();
+    create_archive(&FileSelection::Folder(folder), &archive_path, &config).unwrap();
+
+    let extract_dir = temp_dir.path().join("restored");
+    let result = extract_archive_with_report(&archive_path, &extract_dir, &config).unwrap();
+
+    assert_eq!(result.files.len(), 1);
+    let deepest = extract_dir.join("project/empty/deeper/deepest");
+    assert!(deepest.is_dir());
+    assert_eq!(fs::read_dir(&deepest).unwrap().count(), 0);
+    assert!(result.directories.contains(&deepest));
+    assert!(extract_dir.join("project/src/main.rs").is_file());
+}
+
+#[test]
+fn test_extract_refuses_directory_where_a_file_exists() {
+    let temp_dir = tempdir().unwrap();
+    let folder = temp_dir.path().join("project");
+    fs::create_dir_all(folder.join("reports")).unwrap();
+    create_test_file(&folder, "readme.txt", "hello");
+
+    let archive_path = temp_dir.path().join("project.tar.gz");
+    let config = FileOpsConfig::default();
+    create_archive(&FileSelection::Folder(folder), &archive_path, &config).unwrap();
+
+    // A file already sits where the empty directory would be restored
+    let extract_dir = temp_dir.path().join("restored");
+    fs::create_dir_all(extract_dir.join("project")).unwrap();
+    create_test_file(&extract_dir.join("project"), "reports", "not a directory");
+
+    let result = extract_archive_with_report(&archive_path, &extract_dir, &config);
+
+    assert!(
+        matches!(result, Err(FileOpsError::PathValidationFailed { ref path, .. }) if path.ends_with("reports")),
+        "Expected a path conflict, got {result:?}"
+    );
+    assert_eq!(
+        fs::read_to_string(extract_dir.join("project/reports")).unwrap(),
+        "not a directory"
+    );
+}
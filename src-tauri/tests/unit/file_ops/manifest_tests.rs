@@ -129,3 +129,92 @@ fn test_manifest_integrity_verification() {
     // Verify integrity
     assert!(manifest.verify_integrity().is_ok());
 }
+
+fn file_info(path: &str, size: u64, hash: &str) -> FileInfo {
+    FileInfo {
+        path: PathBuf::from(path),
+        size,
+        modified: Utc::now(),
+        hash: hash.to_string(),
+        #[cfg(unix)]
+        permissions: 0o644,
+    }
+}
+
+fn sample_manifest(files: &[FileInfo]) -> Manifest {
+    let archive_operation = ArchiveOperation {
+        archive_path: PathBuf::from("test.tar.gz"),
+        manifest_path: None,
+        total_size: 100,
+        file_count: files.len(),
+        created: Utc::now(),
+        archive_hash: "test_hash".to_string(),
+    };
+    Manifest::new(&archive_operation, files, &PathBuf::from("test.tar.gz")).unwrap()
+}
+
+/// Streaming verification must agree with loading the manifest, down to the error
+fn assert_verification_parity(manifest: &Manifest, extracted: &[FileInfo]) {
+    let temp_dir = tempdir().unwrap();
+    let manifest_path = temp_dir.path().join("manifest.json");
+    manifest.save(&manifest_path).unwrap();
+    let config = FileOpsConfig::default();
+
+    let loaded = Manifest::load(&manifest_path).unwrap();
+    let in_memory = verify_manifest(&loaded, extracted, &config).map_err(|e| e.to_string());
+    let streamed =
+        verify_manifest_file(&manifest_path, extracted, &config).map_err(|e| e.to_string());
+
+    assert_eq!(streamed, in_memory);
+}
+
+#[test]
+fn test_streaming_verification_matches_in_memory() {
+    let files = vec![
+        file_info("a.txt", 1, "hash-a"),
+        file_info("docs/b.txt", 2, "hash-b"),
+        file_info("docs/c.txt", 3, "hash-c"),
+    ];
+    let manifest = sample_manifest(&files);
+
+    // Matching
+    assert_verification_parity(&manifest, &files);
+    // Different order on disk
+    let reversed: Vec<FileInfo> = files.iter().rev().cloned().collect();
+    assert_verification_parity(&manifest, &reversed);
+    // Missing file
+    assert_verification_parity(&manifest, &files[..2]);
+    // Unknown file with the right count
+    let mut renamed = files.clone();
+    renamed[1].path = PathBuf::from("docs/other.txt");
+    assert_verification_parity(&manifest, &renamed);
+    // Size and hash mismatches; the first in extraction order is reported
+    let mut changed = files.clone();
+    changed[2].size = 30;
+    changed[1].hash = "tampered".to_string();
+    assert_verification_parity(&manifest, &changed);
+    // Empty manifest
+    assert_verification_parity(&sample_manifest(&[]), &[]);
+}
+
+#[test]
+fn test_streaming_verification_rejects_tampered_manifest() {
+    let files = vec![file_info("a.txt", 1, "hash-a")];
+    let mut manifest = sample_manifest(&files);
+    manifest.files[0].size = 2;
+
+    assert_verification_parity(&manifest, &files);
+}
+
+#[test]
+fn test_summary_matches_loaded_manifest() {
+    let temp_dir = tempdir().unwrap();
+    let manifest_path = temp_dir.path().join("manifest.json");
+    let manifest = sample_manifest(&[file_info("a.txt", 5, "x"), file_info("b.txt", 7, "y")]);
+    manifest.save(&manifest_path).unwrap();
+
+    let summary = read_manifest_summary(&manifest_path).unwrap();
+    assert!(summary.indexed);
+    assert_eq!(summary.entry_count, manifest.file_count());
+    assert_eq!(summary.total_size, manifest.total_size());
+}
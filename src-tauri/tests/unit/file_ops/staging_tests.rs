@@ -397,3 +397,67 @@ fn should_create_staging_area_from_selection() {
         "Staging area should have non-zero total size"
     );
 }
+
+// ============================================================================
+// DIRECTORY STAGING TESTS
+// ============================================================================
+
+#[test]
+fn should_keep_directory_whose_files_are_all_excluded() {
+    // Given: A folder with a subdirectory holding only excluded files
+    let temp_dir = tempdir().unwrap();
+    let folder = temp_dir.path().join("docs");
+    fs::create_dir_all(folder.join("locked")).unwrap();
+    create_test_file(&folder, "kept.txt", "kept");
+    let skipped = create_test_file(&folder.join("locked"), "busy.db", "locked");
+
+    // When: Staging the folder with that file excluded
+    let mut staging = StagingArea::new().unwrap();
+    staging.exclude_paths(vec![skipped]);
+    staging
+        .stage_files(&FileSelection::Folder(folder.clone()))
+        .unwrap();
+
+    // Then: The subdirectory is staged empty
+    assert_eq!(staging.file_count(), 1);
+    let staged_dir = staging.path().join("docs").join("locked");
+    assert!(staged_dir.is_dir());
+    assert_eq!(fs::read_dir(&staged_dir).unwrap().count(), 0);
+    assert!(
+        staging
+            .staged_directories()
+            .iter()
+            .any(|directory| directory.path == staged_dir),
+        "Emptied directory should be recorded as a directory entry"
+    );
+}
+
+#[test]
+fn should_leave_out_excluded_subtree() {
+    // Given: A folder holding the app's data directory
+    let temp_dir = tempdir().unwrap();
+    let folder = temp_dir.path().join("home");
+    let app_data = folder.join("appdata");
+    fs::create_dir_all(app_data.join("keys")).unwrap();
+    create_test_file(&folder, "notes.txt", "notes");
+    create_test_file(&app_data.join("keys"), "vault.agekey.enc", "key");
+
+    // When: Staging the folder with the data directory excluded
+    let mut staging = StagingArea::new().unwrap();
+    staging.exclude_paths(vec![app_data]);
+    staging
+        .stage_files(&FileSelection::Folder(folder.clone()))
+        .unwrap();
+
+    // Then: Neither the directory nor anything below it is staged
+    assert_eq!(staging.file_count(), 1);
+    assert!(!staging.path().join("home").join("appdata").exists());
+    assert!(
+        staging
+            .staged_directories()
+            .iter()
+            .all(|directory| !directory.path.ends_with("appdata")
+                && !directory.path.ends_with("keys")),
+        "Excluded directories should not be recorded"
+    );
+}
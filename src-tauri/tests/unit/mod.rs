@@ -13,6 +13,7 @@
 pub mod crypto;
 pub mod file_ops;
 pub mod logging;
+pub mod redaction_audit_tests;
 pub mod storage;
 
 use super::common::{TestSuite, TestSuiteConfig};
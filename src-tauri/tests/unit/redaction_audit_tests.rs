@@ -0,0 +1,190 @@
+//! Audit of key material in bindings and internal types
+//!
+//! Response types are built with canary serials, recipients, identity tags
+//! and key paths, then serialized. A canary may only appear in a field listed
+//! in `PUBLIC_FIELDS`: those are shown in the UI on purpose. Internal types
+//! must not leak canaries through serde or Debug at all.
+
+use barqly_vault_lib::services::key_management::shared::domain::models::{
+    GlobalKey, KeyFileLocation, KeyLifecycleStatus, KeyType, VaultKey, YubiKeyInfo,
+};
+use barqly_vault_lib::services::key_management::yubikey::domain::models::{
+    AvailableYubiKey, PinStatus, Serial, YubiKeyIdentity, YubiKeyState, YubiKeyStateInfo,
+};
+use barqly_vault_lib::services::key_management::yubikey::{AgeHeader, YubiRecipient};
+use barqly_vault_lib::types::Sensitive;
+use chrono::Utc;
+use serde::Serialize;
+use serde_json::Value;
+use std::path::PathBuf;
+
+const SERIAL: &str = "77553311";
+const RECIPIENT: &str = "age1yubikey1canaryrecipientq9";
+const IDENTITY_TAG: &str = "AGE-PLUGIN-YUBIKEY-CANARYTAG00";
+const KEY_DIR: &str = "/Volumes/CANARYDRIVE/private";
+
+const CANARIES: &[&str] = &[SERIAL, RECIPIENT, IDENTITY_TAG, KEY_DIR];
+
+/// Fields the UI displays or sends back, so they carry the raw value
+const PUBLIC_FIELDS: &[&str] = &[
+    "GlobalKey.key_type.data.serial",
+    "GlobalKey.recipient",
+    "GlobalKey.yubikey_info.identity_tag",
+    "VaultKey.data.serial",
+    "AvailableYubiKey.serial",
+    "AvailableYubiKey.recipient",
+    "AvailableYubiKey.identity_tag",
+    "YubiKeyStateInfo.serial",
+    "YubiKeyStateInfo.recipient",
+    "YubiKeyStateInfo.identity_tag",
+];
+
+fn leaked_fields(prefix: &str, value: &Value, out: &mut Vec<String>) {
+    match value {
+        Value::String(s) if CANARIES.iter().any(|canary| s.contains(canary)) => {
+            out.push(prefix.to_string());
+        }
+        Value::Array(items) => {
+            for item in items {
+                leaked_fields(&format!("{prefix}[]"), item, out);
+            }
+        }
+        Value::Object(fields) => {
+            for (name, field) in fields {
+                leaked_fields(&format!("{prefix}.{name}"), field, out);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn assert_only_public_fields_leak<T: Serialize>(type_name: &str, value: &T) {
+    let mut leaked = Vec::new();
+    leaked_fields(
+        type_name,
+        &serde_json::to_value(value).unwrap(),
+        &mut leaked,
+    );
+
+    let unexpected: Vec<_> = leaked
+        .iter()
+        .filter(|field| !PUBLIC_FIELDS.contains(&field.as_str()))
+        .collect();
+    assert!(
+        unexpected.is_empty(),
+        "unredacted canaries in {unexpected:?}"
+    );
+}
+
+fn assert_no_canary(output: &str) {
+    for canary in CANARIES {
+        assert!(!output.contains(canary), "{canary} leaked in {output}");
+    }
+}
+
+fn yubikey_type() -> KeyType {
+    KeyType::YubiKey {
+        serial: SERIAL.to_string(),
+        firmware_version: Some("5.7.1".to_string()),
+    }
+}
+
+#[test]
+fn response_types_expose_only_public_fields() {
+    assert_only_public_fields_leak(
+        "GlobalKey",
+        &GlobalKey {
+            id: "key-1".to_string(),
+            label: "Work".to_string(),
+            key_type: yubikey_type(),
+            recipient: RECIPIENT.to_string(),
+            is_available: true,
+            vault_associations: vec!["vault-1".to_string()],
+            lifecycle_status: KeyLifecycleStatus::Active,
+            created_at: Utc::now(),
+            last_used: None,
+            yubikey_info: Some(YubiKeyInfo {
+                slot: Some(1),
+                identity_tag: Some(IDENTITY_TAG.to_string()),
+                pin_status: PinStatus::Custom,
+                yubikey_state: YubiKeyState::Registered,
+            }),
+            deactivated_at: None,
+        },
+    );
+
+    assert_only_public_fields_leak(
+        "VaultKey",
+        &VaultKey {
+            key_type: yubikey_type(),
+            id: "key-1".to_string(),
+            label: "Work".to_string(),
+            lifecycle_status: KeyLifecycleStatus::Active,
+            created_at: Utc::now(),
+            last_used: None,
+        },
+    );
+
+    assert_only_public_fields_leak(
+        "AvailableYubiKey",
+        &AvailableYubiKey {
+            serial: SERIAL.to_string(),
+            state: "registered".to_string(),
+            lifecycle_status: KeyLifecycleStatus::Active,
+            slot: Some(1),
+            recipient: Some(RECIPIENT.to_string()),
+            identity_tag: Some(IDENTITY_TAG.to_string()),
+            label: Some("Work".to_string()),
+            pin_status: "custom".to_string(),
+        },
+    );
+
+    assert_only_public_fields_leak(
+        "YubiKeyStateInfo",
+        &YubiKeyStateInfo {
+            serial: SERIAL.to_string(),
+            state: YubiKeyState::Registered,
+            lifecycle_status: KeyLifecycleStatus::Active,
+            slot: Some(1),
+            recipient: Some(RECIPIENT.to_string()),
+            identity_tag: Some(IDENTITY_TAG.to_string()),
+            label: Some("Work".to_string()),
+            pin_status: PinStatus::Custom,
+            firmware_version: None,
+            has_tdes_protected_mgmt_key: true,
+            created_at: Utc::now(),
+            last_used: None,
+            reader_name: None,
+        },
+    );
+}
+
+#[test]
+fn internal_types_redact_key_material() {
+    let recipient = YubiRecipient {
+        recipient: Sensitive::new(RECIPIENT.to_string()),
+        label: "Work".to_string(),
+        serial: Sensitive::new(SERIAL.to_string()),
+        slot: 0x9c,
+    };
+    assert_no_canary(&serde_json::to_string(&recipient).unwrap());
+    assert_no_canary(&format!("{recipient:?}"));
+    assert_eq!(recipient.recipient.expose_for_bindings(), RECIPIENT);
+
+    let header = AgeHeader {
+        data: Vec::new(),
+        recipients: vec![Sensitive::new(RECIPIENT.to_string())],
+    };
+    assert_no_canary(&serde_json::to_string(&header).unwrap());
+    assert_no_canary(&format!("{header:?}"));
+
+    let serial = Serial::from_str(SERIAL).unwrap();
+    assert_no_canary(&format!("{serial:?}"));
+
+    let identity =
+        YubiKeyIdentity::new(IDENTITY_TAG.to_string(), serial, RECIPIENT.to_string()).unwrap();
+    assert_no_canary(&format!("{identity:?}"));
+
+    let location = KeyFileLocation::for_path(&PathBuf::from(KEY_DIR).join("work.agekey.enc"), &[]);
+    assert_no_canary(&format!("{location:?}"));
+}
@@ -1,5 +1,6 @@
 // @ts-nocheck - Suppress TypeScript warnings for unused generated code
 // This file is auto-generated by tauri-specta. Do not edit manually.
+// Bindings version: 2
 // This file was generated by [tauri-specta](https://github.com/oscartbeaumont/tauri-specta). Do not edit this file manually.
 
 /** user-defined commands **/
@@ -124,9 +125,9 @@ async analyzeEncryptedVault(input: AnalyzeEncryptedVaultRequest) : Promise<Resul
 /**
  * List keys with flexible filtering options - unified API
  */
-async listUnifiedKeys(filter: KeyListFilter) : Promise<Result<GlobalKey[], CommandError>> {
+async listUnifiedKeys(filter: KeyListFilter, sort: SortSpec<KeySortField> | null, page: PageRequest | null) : Promise<Result<ListUnifiedKeysResponse, CommandError>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("list_unified_keys", { filter }) };
+    return { status: "ok", data: await TAURI_INVOKE("list_unified_keys", { filter, sort, page }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
@@ -344,11 +345,11 @@ async createVault(input: CreateVaultRequest) : Promise<Result<CreateVaultRespons
 }
 },
 /**
- * List all vaults
+ * List vaults, one page at a time
  */
-async listVaults() : Promise<Result<ListVaultsResponse, CommandError>> {
+async listVaults(sort: SortSpec<VaultSortField> | null, page: PageRequest | null) : Promise<Result<ListVaultsResponse, CommandError>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("list_vaults") };
+    return { status: "ok", data: await TAURI_INVOKE("list_vaults", { sort, page }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
@@ -538,9 +539,9 @@ async initYubikey(serial: string, newPin: string, recoveryPin: string, label: st
  * 2. Generate age identity (requires touch)
  * 3. Register in global registry
  */
-async completeYubikeySetup(serial: string, pin: string, label: string) : Promise<Result<StreamlinedYubiKeyInitResult, CommandError>> {
+async completeYubikeySetup(serial: string, pin: string, label: string, acceptLastAttempt: boolean | null) : Promise<Result<StreamlinedYubiKeyInitResult, CommandError>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("complete_yubikey_setup", { serial, pin, label }) };
+    return { status: "ok", data: await TAURI_INVOKE("complete_yubikey_setup", { serial, pin, label, acceptLastAttempt }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
@@ -554,9 +555,9 @@ async completeYubikeySetup(serial: string, pin: string, label: string) : Promise
  * 1. Generates age identity (requires touch)
  * 2. Registers in global registry
  */
-async generateYubikeyIdentity(serial: string, pin: string, label: string) : Promise<Result<StreamlinedYubiKeyInitResult, CommandError>> {
+async generateYubikeyIdentity(serial: string, pin: string, label: string, acceptLastAttempt: boolean | null) : Promise<Result<StreamlinedYubiKeyInitResult, CommandError>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("generate_yubikey_identity", { serial, pin, label }) };
+    return { status: "ok", data: await TAURI_INVOKE("generate_yubikey_identity", { serial, pin, label, acceptLastAttempt }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
@@ -600,6 +601,74 @@ async yubikeyDecryptFile(encryptedFile: string, unlockMethod: UnlockMethod | nul
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
+},
+/**
+ * Record that the interface loaded and check its bindings match this build
+ * 
+ * A `drifted` result means the interface was built against other bindings;
+ * commands may fail or misread their responses until the app is reinstalled.
+ */
+async frontendReady(input: FrontendReadyInput) : Promise<Result<FrontendHandshake, CommandError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("frontend_ready", { input }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Write the record of the latest launch whose interface didn't load into a
+ * folder, returning the file's path
+ * 
+ * Offered by the `launch_failed` notification; dismissing that notification
+ * forgets the record.
+ */
+async exportLaunchDiagnostics(input: ExportLaunchDiagnosticsInput) : Promise<Result<string, CommandError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("export_launch_diagnostics", { input }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Put a value on the clipboard, to be cleared after the configured time
+ * 
+ * Recovery codes can only be copied for a few minutes after their shares
+ * are created, and only with the confirmation. The clear is skipped if
+ * something else has been copied since.
+ */
+async copyToClipboard(input: CopyToClipboardInput) : Promise<Result<ClipboardGuardStatus, CommandError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("copy_to_clipboard", { input }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Clipboard settings and the copy waiting to be cleared, for a countdown
+ */
+async getClipboardGuardStatus() : Promise<Result<ClipboardGuardStatus, CommandError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_clipboard_guard_status") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Turn clipboard copying on or off and set how long copies stay
+ * 
+ * A copy already on the clipboard keeps the time it was copied with.
+ */
+async setClipboardSettings(input: SetClipboardSettingsInput) : Promise<Result<null, CommandError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_clipboard_settings", { input }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
 }
 }
 
@@ -695,6 +764,64 @@ vault_id: string }
  * Response from key attachment
  */
 export type AttachKeyToVaultResponse = { success: boolean; message: string; key_id: string; vault_id: string }
+/**
+ * How the interface's bindings compare with the backend's
+ */
+export type BindingsDrift = 
+/**
+ * Built against the bindings this backend generates
+ */
+"in_sync" | 
+/**
+ * Built against other bindings; commands may fail or misread responses
+ */
+"drifted" | 
+/**
+ * The backend couldn't render its bindings to compare
+ */
+"unknown"
+/**
+ * A size in bytes
+ * 
+ * Serialized as a plain number of bytes.
+ */
+export type ByteSize = number
+/**
+ * Clipboard settings and the copy waiting to be cleared, if any
+ */
+export type ClipboardGuardStatus = { settings: ClipboardSettings; guarded: GuardedCopy | null }
+/**
+ * Kind of value copied
+ */
+export type ClipboardKind = 
+/**
+ * A key's or contact's age recipient
+ */
+"recipient" | 
+/**
+ * One share of a vault's recovery secret, while it is still held
+ */
+"recovery_code" | 
+/**
+ * Short fingerprint of a key's or contact's recipient
+ */
+"fingerprint" | 
+/**
+ * Location of an archive file on this device
+ */
+"archive_path"
+/**
+ * Device-wide clipboard settings
+ */
+export type ClipboardSettings = { 
+/**
+ * Copying is off until the user turns it on
+ */
+enabled?: boolean; 
+/**
+ * Time before a copy is cleared from the clipboard
+ */
+clear_after?: DurationMs }
 /**
  * Unified error type for all commands with comprehensive error information
  * 
@@ -747,6 +874,23 @@ trace_id: string | null;
  * Optional span ID for debugging
  */
 span_id: string | null }
+/**
+ * A value to copy, by reference
+ */
+export type CopyToClipboardInput = { kind: ClipboardKind; 
+/**
+ * Key or contact ID for recipients and fingerprints, share number
+ * (from 1) for recovery codes, archive ID for archive paths
+ */
+value_ref: string; 
+/**
+ * Vault the recovery code or archive belongs to
+ */
+vault_id?: string | null; 
+/**
+ * For recovery codes, the user must type "COPY"
+ */
+confirmation?: string | null }
 /**
  * Input for creating a new vault
  */
@@ -821,6 +965,12 @@ export type DeleteVaultRequest = { vault_id: string; force: boolean }
  * Response from vault deletion
  */
 export type DeleteVaultResponse = { success: boolean; message: string }
+/**
+ * A duration in milliseconds
+ * 
+ * Serialized as a plain number of milliseconds.
+ */
+export type DurationMs = number
 /**
  * Input for encryption command
  */
@@ -845,7 +995,11 @@ export type EncryptionStatus = "Pending" | "InProgress" | "Completed" | "Failed"
 /**
  * Response from encryption status command
  */
-export type EncryptionStatusResponse = { operation_id: string; status: EncryptionStatus; progress_percentage: number; current_file: string | null; total_files: number; processed_files: number; total_size: number; processed_size: number; estimated_time_remaining: number | null; error_message: string | null }
+export type EncryptionStatusResponse = { operation_id: string; status: EncryptionStatus; progress_percentage: number; current_file: string | null; total_files: number; processed_files: number; total_size: ByteSize; processed_size: ByteSize; estimated_time_remaining: DurationMs | null; error_message: string | null; 
+/**
+ * Display forms of `total_size` and `estimated_time_remaining`
+ */
+format_hints: FormatHints | null }
 /**
  * Error codes for client-side handling and internationalization
  * 
@@ -889,15 +1043,66 @@ exported_file: string;
 /**
  * Size of the exported file in bytes
  */
-file_size: number }
+file_size: ByteSize }
+/**
+ * Input for exporting launch diagnostics
+ */
+export type ExportLaunchDiagnosticsInput = { 
+/**
+ * Folder to write the diagnostic record into
+ */
+destination_dir: string }
 /**
  * File information
  */
-export type FileInfo = { path: string; name: string; size: number; is_file: boolean; is_directory: boolean; file_count: number | null }
+export type FileInfo = { path: string; name: string; size: ByteSize; is_file: boolean; is_directory: boolean; file_count: number | null }
 /**
  * File selection result
  */
-export type FileSelection = { paths: string[]; total_size: number; file_count: number; selection_type: string }
+export type FileSelection = { paths: string[]; total_size: ByteSize; file_count: number; selection_type: string }
+/**
+ * Optional display strings that accompany typed size/duration fields
+ * 
+ * Produced only by [`FormatHints::size`], [`FormatHints::duration`] and
+ * [`FormatHints::new`] so every response formats units the same way.
+ */
+export type FormatHints = { 
+/**
+ * Formatted primary size of the response, e.g. `1.5 MB`
+ */
+size?: string | null; 
+/**
+ * Formatted primary duration of the response, e.g. `1 min 30 s`
+ */
+duration?: string | null }
+/**
+ * The interface's `frontend_ready` call
+ */
+export type FrontendHandshake = { ui_version: string; 
+/**
+ * `BINDINGS_HASH` the interface was built with
+ */
+bindings_hash: string; 
+/**
+ * Hash of the bindings this backend generates, when it could be rendered
+ */
+expected_bindings_hash: string | null; drift: BindingsDrift; received_at: string; 
+/**
+ * Time from the backend starting the window to the handshake
+ */
+elapsed: DurationMs }
+/**
+ * The interface's handshake, sent as its first action
+ */
+export type FrontendReadyInput = { 
+/**
+ * Version of the interface build
+ */
+ui_version: string; 
+/**
+ * `BINDINGS_HASH` from the bindings the interface was built with
+ */
+bindings_hash: string }
 export type GenerateKeyInput = { label: string; passphrase: string }
 export type GenerateKeyResponse = { public_key: string; key_id: string; saved_path: string }
 /**
@@ -935,7 +1140,11 @@ export type GetProgressInput = { operation_id: string }
 /**
  * Response from progress status command
  */
-export type GetProgressResponse = { operation_id: string; progress: number; message: string; details: ProgressDetails | null; timestamp: string; estimated_time_remaining: number | null; is_complete: boolean }
+export type GetProgressResponse = { operation_id: string; progress: number; message: string; details: ProgressDetails | null; timestamp: string; estimated_time_remaining: DurationMs | null; is_complete: boolean; 
+/**
+ * Display form of `estimated_time_remaining`
+ */
+format_hints: FormatHints | null }
 /**
  * Input for getting vault keys
  */
@@ -1019,7 +1228,19 @@ deactivated_at?: string | null }
 /**
  * Summary statistics across all vaults
  */
-export type GlobalVaultStatistics = { total_vaults: number; active_vaults: number; new_vaults: number; orphaned_vaults: number; total_encryptions: number; total_files: number; total_size_bytes: number; vault_statistics: VaultStatistics[] }
+export type GlobalVaultStatistics = { total_vaults: number; active_vaults: number; new_vaults: number; orphaned_vaults: number; total_encryptions: number; total_files: number; total_size_bytes: ByteSize; vault_statistics: VaultStatistics[]; 
+/**
+ * Display form of `total_size_bytes`
+ */
+format_hints: FormatHints | null }
+/**
+ * A copy waiting to be cleared
+ */
+export type GuardedCopy = { kind: ClipboardKind; copied_at: string; clears_at: string; 
+/**
+ * Time left until it is cleared, for a countdown
+ */
+remaining: DurationMs }
 /**
  * Request to import a key file
  */
@@ -1116,6 +1337,10 @@ export type KeyListFilter =
  * Simplified key metadata for frontend
  */
 export type KeyMetadata = { label: string; created_at: string; public_key: string }
+/**
+ * Fields `list_unified_keys` can sort by
+ */
+export type KeySortField = "label" | "created_at" | "last_used"
 /**
  * Key statistics for a vault
  */
@@ -1149,14 +1374,46 @@ firmware_version?: string | null } } |
  * Used for encrypting to other people's keys
  */
 { type: "Recipient" }
+/**
+ * One page of keys
+ */
+export type ListUnifiedKeysResponse = { 
+/**
+ * Sorted by label unless asked otherwise
+ */
+keys: GlobalKey[]; 
+/**
+ * Keys matching the filter, in all pages
+ */
+total_count: number }
 /**
  * Response containing list of vaults
  */
-export type ListVaultsResponse = { vaults: VaultSummary[] }
+export type ListVaultsResponse = { 
+/**
+ * The requested page, sorted by name unless asked otherwise
+ */
+vaults: VaultSummary[]; 
+/**
+ * Vaults in all pages
+ */
+total_count: number; 
+/**
+ * Change revision the list reflects (see `registry-changed` events)
+ */
+registry_revision: number }
 /**
  * Manifest for encrypted archives
  */
-export type Manifest = { version: string; created_at: string; files: FileInfo[]; total_size: number; file_count: number }
+export type Manifest = { version: string; created_at: string; files: FileInfo[]; total_size: ByteSize; file_count: number }
+/**
+ * Which slice of a sorted list to return
+ */
+export type PageRequest = { offset?: number; 
+/**
+ * Defaults to `DEFAULT_PAGE_LIMIT`, capped at `MAX_PAGE_LIMIT`
+ */
+limit: number | null }
 export type PassphraseStrength = "weak" | "fair" | "good" | "strong"
 export type PassphraseValidationResult = { is_valid: boolean; strength: PassphraseStrength; feedback: string[]; score: number }
 /**
@@ -1183,19 +1440,19 @@ export type ProgressDetails =
 /**
  * File operation progress (copying, moving, etc.)
  */
-{ type: "FileOperation"; current_file: string; total_files: number; current_file_progress: number; current_file_size: number; total_size: number } | 
+{ type: "FileOperation"; current_file: string; total_files: number; current_file_progress: number; current_file_size: ByteSize; total_size: ByteSize } | 
 /**
  * Encryption operation progress
  */
-{ type: "Encryption"; bytes_processed: number; total_bytes: number; encryption_rate: number | null } | 
+{ type: "Encryption"; bytes_processed: ByteSize; total_bytes: ByteSize; encryption_rate: number | null } | 
 /**
  * Decryption operation progress
  */
-{ type: "Decryption"; bytes_processed: number; total_bytes: number; decryption_rate: number | null } | 
+{ type: "Decryption"; bytes_processed: ByteSize; total_bytes: ByteSize; decryption_rate: number | null } | 
 /**
  * Archive operation progress (compression, extraction)
  */
-{ type: "ArchiveOperation"; files_processed: number; total_files: number; bytes_processed: number; total_bytes: number; compression_ratio: number | null } | 
+{ type: "ArchiveOperation"; files_processed: number; total_files: number; bytes_processed: ByteSize; total_bytes: ByteSize; compression_ratio: number | null } | 
 /**
  * Manifest operation progress (verification, generation)
  */
@@ -1240,6 +1497,10 @@ restored_at: string }
  * File selection type
  */
 export type SelectionType = "Files" | "Folder"
+/**
+ * Whether values may be copied, and for how long they stay
+ */
+export type SetClipboardSettingsInput = { settings: ClipboardSettings }
 /**
  * Input for setting current vault
  */
@@ -1248,6 +1509,11 @@ export type SetCurrentVaultRequest = { vault_id: string }
  * Response from setting current vault
  */
 export type SetCurrentVaultResponse = { success: boolean; vault: VaultSummary }
+export type SortDirection = "asc" | "desc"
+/**
+ * Field and direction to sort a list by
+ */
+export type SortSpec<F> = { field: F; direction?: SortDirection }
 export type StreamlinedYubiKeyInitResult = { serial: string; slot: number; recipient: string; identity_tag: string; label: string; operation_id: string }
 /**
  * Credentials for unlocking vaults
@@ -1367,10 +1633,18 @@ created_at: string;
  * Last time this key was used
  */
 last_used: string | null }
+/**
+ * Fields `list_vaults` can sort by
+ */
+export type VaultSortField = "name" | "created_at" | "key_count"
 /**
  * Statistics for a single vault
  */
-export type VaultStatistics = { vault_id: string; vault_name: string; description: string | null; status: VaultStatus; encryption_count: number; created_at: string; last_encrypted_at: string | null; last_encrypted_by: string | null; file_count: number; total_size_bytes: number; key_statistics: KeyStatistics; archive_exists: boolean; manifest_exists: boolean }
+export type VaultStatistics = { vault_id: string; vault_name: string; description: string | null; status: VaultStatus; encryption_count: number; created_at: string; last_encrypted_at: string | null; last_encrypted_by: string | null; file_count: number; total_size_bytes: ByteSize; key_statistics: KeyStatistics; archive_exists: boolean; manifest_exists: boolean; 
+/**
+ * Display form of `total_size_bytes`
+ */
+format_hints: FormatHints | null }
 /**
  * Vault status based on encryption history
  */
@@ -1404,7 +1678,7 @@ export type VerifyManifestInput = { manifest_path: string; extracted_files_dir:
 /**
  * Response from manifest verification command
  */
-export type VerifyManifestResponse = { is_valid: boolean; message: string; file_count: number; total_size: number }
+export type VerifyManifestResponse = { is_valid: boolean; message: string; file_count: number; total_size: ByteSize }
 /**
  * YubiKey-specific information for unified API
  */
@@ -1554,4 +1828,6 @@ function __makeEvents__<T extends Record<string, any>>(
 			},
 		},
 	);
-}
\ No newline at end of file
+}
+
+export const BINDINGS_HASH = "4efc57d76a907e37714d50ae86df7c45057c9c894b5385bbf5e529956c9663e2";
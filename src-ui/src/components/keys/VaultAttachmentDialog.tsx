@@ -49,7 +49,7 @@ export const VaultAttachmentDialog: React.FC<VaultAttachmentDialogProps> = ({
         });
 
         // Get all vaults
-        const vaultsResult = await commands.listVaults();
+        const vaultsResult = await commands.listVaults(null, null);
         logger.info('VaultAttachmentDialog', 'listVaults result', {
           status: vaultsResult.status,
           result: vaultsResult,
@@ -55,14 +55,18 @@ export const YubiKeySetupDialog: React.FC<YubiKeySetupDialogProps> = ({
       }
 
       // Get available YubiKeys for this vault using unified API
-      const result = await commands.listUnifiedKeys({
-        type: 'AvailableForVault',
-        value: currentVault.id,
-      });
+      const result = await commands.listUnifiedKeys(
+        {
+          type: 'AvailableForVault',
+          value: currentVault.id,
+        },
+        null,
+        null,
+      );
       if (result.status === 'error') {
         throw new Error(result.error.message || 'Failed to list available YubiKeys');
       }
-      const unifiedKeys = result.data;
+      const unifiedKeys = result.data.keys;
 
       // Convert unified KeyInfo to AvailableYubiKey format for compatibility
       const keys = unifiedKeys.map((key) => ({
@@ -262,6 +262,7 @@ export const useYubiKeyRegistration = ({
             selectedKey.serial,
             pin,
             label.trim(),
+            null, // Refused with one PIN attempt left until the user accepts it
           );
 
           if (completeResult.status === 'error') {
@@ -303,6 +304,7 @@ export const useYubiKeyRegistration = ({
             selectedKey.serial,
             pin,
             label.trim(),
+            null, // Refused with one PIN attempt left until the user accepts it
           );
 
           if (generateResult.status === 'error') {
@@ -121,7 +121,8 @@ function ProgressBar({
   // Get time remaining from progress update or props
   const getTimeRemaining = (): string => {
     if (progressUpdate?.estimated_time_remaining) {
-      return formatTimeRemaining(progressUpdate.estimated_time_remaining);
+      // Backend reports milliseconds
+      return formatTimeRemaining(progressUpdate.estimated_time_remaining / 1000);
     }
     return formatTimeRemaining(estimatedTimeRemaining);
   };
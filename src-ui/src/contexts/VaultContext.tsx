@@ -178,7 +178,7 @@ export const VaultProvider: React.FC<{ children: ReactNode }> = ({ children }) =
     try {
       // Get all vaults
       logger.debug('VaultContext', 'Calling listVaults');
-      const vaultsResult = await commands.listVaults();
+      const vaultsResult = await commands.listVaults(null, null);
       logger.debug('VaultContext', 'listVaults response', vaultsResult);
 
       if (vaultsResult.status === 'error') {
@@ -295,16 +295,16 @@ export const VaultProvider: React.FC<{ children: ReactNode }> = ({ children }) =
     try {
       logger.info('VaultContext', 'Fetching all keys from global registry');
 
-      const result = await commands.listUnifiedKeys({ type: 'All' });
+      const result = await commands.listUnifiedKeys({ type: 'All' }, null, null);
 
       if (result.status === 'error') {
         throw new Error(result.error.message || 'Failed to list global keys');
       }
 
-      setGlobalKeyCache(result.data);
+      setGlobalKeyCache(result.data.keys);
 
       logger.info('VaultContext', 'Global keys cached', {
-        keyCount: result.data.length,
+        keyCount: result.data.keys.length,
       });
     } catch (err: any) {
       logger.error('VaultContext', 'Failed to refresh global keys', err);
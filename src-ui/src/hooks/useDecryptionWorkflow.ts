@@ -184,10 +184,10 @@ export const useDecryptionWorkflow = () => {
             setSuggestedKeys(registeredKeys);
           } else if (!vaultContextInitialized) {
             // VaultContext might still be loading, fall back to direct API call
-            const allKeysResult = await commands.listUnifiedKeys({ type: 'All' });
+            const allKeysResult = await commands.listUnifiedKeys({ type: 'All' }, null, null);
             if (allKeysResult.status === 'ok') {
               // Filter out destroyed keys and recipients (recipients can't decrypt)
-              const usableGlobalKeys = allKeysResult.data.filter(
+              const usableGlobalKeys = allKeysResult.data.keys.filter(
                 (k) => k.lifecycle_status !== 'destroyed' && k.key_type.type !== 'Recipient',
               );
               const registeredKeys = usableGlobalKeys.map((keyInfo): VaultKey => {
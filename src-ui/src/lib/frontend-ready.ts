@@ -0,0 +1,38 @@
+/**
+ * Startup handshake with the backend
+ *
+ * Tells the backend the interface loaded, so its launch watchdog doesn't
+ * record the launch as failed, and reports which bindings this build uses.
+ */
+
+import { BINDINGS_HASH, commands } from '../bindings';
+import { isTauri } from './environment/platform';
+import { logger } from './logger';
+import { version } from '../../package.json';
+
+/**
+ * Send `frontend_ready` once, as the interface's first action
+ *
+ * Never throws: a failed handshake is logged and startup carries on.
+ */
+export async function announceFrontendReady(): Promise<void> {
+  if (!isTauri()) {
+    return;
+  }
+
+  try {
+    const result = await commands.frontendReady({
+      ui_version: version,
+      bindings_hash: BINDINGS_HASH,
+    });
+    if (result.status === 'error') {
+      logger.warn('FrontendReady', 'Backend rejected the startup handshake', result.error);
+    } else if (result.data.drift === 'drifted') {
+      logger.warn('FrontendReady', 'Interface was built against other bindings', result.data);
+    } else {
+      logger.info('FrontendReady', 'Startup handshake sent', { drift: result.data.drift });
+    }
+  } catch (error) {
+    logger.error('FrontendReady', 'Failed to send the startup handshake', error as Error);
+  }
+}